@@ -0,0 +1,110 @@
+//! Geordnetes Beenden des Clients
+//!
+//! Schliesst der Benutzer das Fenster mitten im Gespraech, haelt `lib.rs`
+//! das Schliessen an und ruft [`herunterfahren`] auf. Innerhalb von
+//! [`BEENDEN_FRIST`] laufen parallel:
+//!
+//! - Voice-Pipeline stoppen (Audio-Thread, UDP-Empfang)
+//! - Voice-Trace abschliessen (Datei flushen)
+//! - auf der Control-Verbindung nacheinander Voice-Disconnect, Kanal
+//!   verlassen und [`ServerConnection::close_gracefully`], also Logout als
+//!   letzter Frame statt eines TCP-Resets
+//!
+//! Anfragen, die waehrenddessen auf die Verbindung warten, scheitern danach
+//! mit [`ConnectionError::Beendet`](crate::connection::ConnectionError).
+//! Uploads brauchen keinen eigenen Schritt: der Client fordert nur das
+//! Upload-Token an, es laeuft also nie ein Upload, der verworfen werden
+//! muesste.
+
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::time::Instant;
+use tracing::{debug, info, warn};
+
+use crate::connection::ServerConnection;
+use crate::state::AppState;
+
+/// Obergrenze fuer den gesamten Ablauf; danach endet der Prozess trotzdem
+pub const BEENDEN_FRIST: Duration = Duration::from_secs(2);
+
+/// Anteil der Frist, der dem Logout am Ende sicher bleibt
+const LOGOUT_RESERVE: Duration = Duration::from_millis(500);
+
+/// Fuehrt den Beenden-Ablauf aus (nur beim ersten Aufruf)
+///
+/// Gibt `false` zurueck, wenn bereits ein Ablauf laeuft oder gelaufen ist.
+pub async fn herunterfahren(state: &AppState) -> bool {
+    if state.beendet.swap(true, Ordering::SeqCst) {
+        return false;
+    }
+    info!("Client wird beendet");
+    let ende = Instant::now() + BEENDEN_FRIST;
+
+    let voice = async {
+        let mut voice = state.voice.lock().await;
+        if let Some(ref mut client) = *voice {
+            client.stop().await;
+        }
+        *voice = None;
+    };
+    let trace = async {
+        let trace = Arc::clone(&state.voice_trace);
+        if let Ok(Some(bericht)) = tokio::task::spawn_blocking(move || trace.stoppen()).await {
+            debug!(
+                pakete = bericht.pakete,
+                "Voice-Trace beim Beenden abgeschlossen"
+            );
+        }
+    };
+
+    let ablauf = async { tokio::join!(voice, trace, verbindung_beenden(state, ende)) };
+    if tokio::time::timeout_at(ende, ablauf).await.is_err() {
+        warn!("Beenden nach {} s abgebrochen", BEENDEN_FRIST.as_secs_f32());
+    }
+    true
+}
+
+/// Meldet Voice und Kanal ab und schliesst die Control-Verbindung
+async fn verbindung_beenden(state: &AppState, ende: Instant) {
+    let kanal = state
+        .connection
+        .lock()
+        .ok()
+        .and_then(|c| c.current_channel.clone());
+
+    let mut tcp = state.tcp.lock().await;
+    let Some(conn) = tcp.as_mut() else {
+        return;
+    };
+
+    let schritte = abmelden(conn, kanal.as_deref());
+    if tokio::time::timeout_at(ende - LOGOUT_RESERVE, schritte)
+        .await
+        .is_err()
+    {
+        warn!("Voice-Disconnect/Kanal verlassen beim Beenden ohne Antwort");
+    }
+
+    let rest = ende.saturating_duration_since(Instant::now());
+    match conn.close_gracefully(rest).await {
+        Ok(()) => info!("Beim Server abgemeldet"),
+        Err(e) => warn!("Logout beim Beenden fehlgeschlagen: {}", e),
+    }
+}
+
+/// Voice-Disconnect und Kanal verlassen (Fehler werden nur protokolliert)
+async fn abmelden(conn: &mut ServerConnection, kanal: Option<&str>) {
+    if let Err(e) = conn
+        .voice_disconnect(Some("Client beendet".to_string()))
+        .await
+    {
+        debug!("Voice-Disconnect beim Beenden: {}", e);
+    }
+    if let Some(kanal) = kanal {
+        if let Err(e) = conn.leave_channel(kanal).await {
+            debug!("Kanal verlassen beim Beenden: {}", e);
+        }
+    }
+}
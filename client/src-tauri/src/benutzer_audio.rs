@@ -0,0 +1,364 @@
+//! Lautstaerke und Stummschaltung pro Benutzer
+//!
+//! Die Einstellungen gelten pro Benutzer-ID und ueberdauern Sitzungen – SSRCs
+//! wechseln dagegen bei jedem Beitritt. Das Frontend speichert die Tabelle mit
+//! den uebrigen Client-Einstellungen und reicht sie beim Start herein; jede
+//! Aenderung liefert die aktualisierte Tabelle zum Speichern zurueck.
+//!
+//! Welcher Benutzer hinter einer SSRC steckt, kommt aus der
+//! [`SsrcZuordnung`] der Verbindung. Der Empfangs-Mixer fragt pro Paket den
+//! Pegel seiner SSRC ab und wendet ihn ab dem ersten zugeordneten Paket an.
+//!
+//! Eintraege von Benutzern, die laenger als `retention_days` nicht mehr
+//! gehoert wurden, werden beim Laden und beim Verbinden entfernt.
+
+use std::collections::HashMap;
+use std::sync::{Mutex, MutexGuard};
+
+use serde::{Deserialize, Serialize};
+use speakeasy_core::types::UserId;
+use speakeasy_protocol::ssrc::SsrcZuordnung;
+
+/// Standard-Aufbewahrung nicht mehr gehoerter Benutzer
+pub const STANDARD_AUFBEWAHRUNG_TAGE: u32 = 90;
+
+/// Hoechste Verstaerkung pro Benutzer (wie `VolumeController`)
+pub const MAX_GAIN: f32 = 2.0;
+
+const SEKUNDEN_PRO_TAG: u64 = 24 * 60 * 60;
+
+/// Gespeicherte Einstellung fuer einen Benutzer
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BenutzerAudio {
+    /// Verstaerkung (0.0 - 2.0, 1.0 = unveraendert)
+    pub gain: f32,
+    /// Lokal stumm geschaltet
+    pub muted: bool,
+    /// Zuletzt gehoert bzw. geaendert (Unix-Sekunden)
+    pub last_seen: u64,
+}
+
+impl BenutzerAudio {
+    fn neu(jetzt: u64) -> Self {
+        Self {
+            gain: 1.0,
+            muted: false,
+            last_seen: jetzt,
+        }
+    }
+}
+
+/// Alle gespeicherten Einstellungen pro Benutzer
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BenutzerAudioEinstellungen {
+    #[serde(default)]
+    pub users: HashMap<UserId, BenutzerAudio>,
+    /// Nach so vielen Tagen ohne Kontakt wird ein Eintrag entfernt
+    #[serde(default = "standard_aufbewahrung")]
+    pub retention_days: u32,
+}
+
+fn standard_aufbewahrung() -> u32 {
+    STANDARD_AUFBEWAHRUNG_TAGE
+}
+
+impl Default for BenutzerAudioEinstellungen {
+    fn default() -> Self {
+        Self {
+            users: HashMap::new(),
+            retention_days: STANDARD_AUFBEWAHRUNG_TAGE,
+        }
+    }
+}
+
+impl BenutzerAudioEinstellungen {
+    /// Entfernt Eintraege, die laenger als die Aufbewahrung nicht gehoert wurden
+    ///
+    /// Gibt die Anzahl entfernter Eintraege zurueck.
+    pub fn bereinigen(&mut self, jetzt: u64) -> usize {
+        let grenze = jetzt.saturating_sub(u64::from(self.retention_days) * SEKUNDEN_PRO_TAG);
+        let vorher = self.users.len();
+        self.users.retain(|_, audio| audio.last_seen >= grenze);
+        vorher - self.users.len()
+    }
+
+    fn eintrag(&mut self, user_id: UserId, jetzt: u64) -> &mut BenutzerAudio {
+        let eintrag = self
+            .users
+            .entry(user_id)
+            .or_insert_with(|| BenutzerAudio::neu(jetzt));
+        eintrag.last_seen = jetzt;
+        eintrag
+    }
+}
+
+/// Wirksame Einstellung eines Benutzers samt aktueller SSRC
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BenutzerLautstaerke {
+    pub user_id: UserId,
+    /// SSRC im aktuellen Kanal (`None` = nicht im Kanal)
+    pub ssrc: Option<u32>,
+    pub gain: f32,
+    pub muted: bool,
+}
+
+/// Geteilter Zustand zwischen Befehlen, Verbindung und Empfangs-Mixer
+#[derive(Debug, Default)]
+pub struct BenutzerPegel {
+    zustand: Mutex<PegelZustand>,
+}
+
+#[derive(Debug, Default)]
+struct PegelZustand {
+    einstellungen: BenutzerAudioEinstellungen,
+    zuordnung: SsrcZuordnung,
+}
+
+impl BenutzerPegel {
+    pub fn neu() -> Self {
+        Self::default()
+    }
+
+    fn zustand(&self) -> MutexGuard<'_, PegelZustand> {
+        self.zustand.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Kopie der Einstellungen (zum Speichern bzw. Exportieren)
+    pub fn einstellungen(&self) -> BenutzerAudioEinstellungen {
+        self.zustand().einstellungen.clone()
+    }
+
+    /// Ersetzt die Einstellungen (z.B. beim Start oder Import) und bereinigt sie
+    ///
+    /// Gibt die Anzahl entfernter veralteter Eintraege zurueck.
+    pub fn laden(&self, mut einstellungen: BenutzerAudioEinstellungen, jetzt: u64) -> usize {
+        let entfernt = einstellungen.bereinigen(jetzt);
+        self.zustand().einstellungen = einstellungen;
+        entfernt
+    }
+
+    /// Entfernt veraltete Eintraege
+    pub fn bereinigen(&self, jetzt: u64) -> usize {
+        self.zustand().einstellungen.bereinigen(jetzt)
+    }
+
+    /// Setzt die Verstaerkung eines Benutzers
+    pub fn lautstaerke_setzen(&self, user_id: UserId, gain: f32, jetzt: u64) {
+        self.zustand().einstellungen.eintrag(user_id, jetzt).gain = gain.clamp(0.0, MAX_GAIN);
+    }
+
+    /// Schaltet einen Benutzer lokal stumm oder wieder laut
+    pub fn stumm_setzen(&self, user_id: UserId, muted: bool, jetzt: u64) {
+        self.zustand().einstellungen.eintrag(user_id, jetzt).muted = muted;
+    }
+
+    /// Vergisst die Einstellung eines Benutzers (`None` = aller Benutzer)
+    pub fn zuruecksetzen(&self, user_id: Option<&UserId>) {
+        let mut zustand = self.zustand();
+        match user_id {
+            Some(user_id) => {
+                zustand.einstellungen.users.remove(user_id);
+            }
+            None => zustand.einstellungen.users.clear(),
+        }
+    }
+
+    /// Uebernimmt die aktuelle SSRC-Zuordnung der Verbindung
+    pub fn zuordnung_setzen(&self, zuordnung: &SsrcZuordnung) {
+        self.zustand().zuordnung = zuordnung.clone();
+    }
+
+    /// Benutzer hinter einer SSRC und dessen gespeicherte Einstellung
+    ///
+    /// `None` solange die SSRC niemandem zugeordnet ist.
+    pub fn fuer_ssrc(&self, ssrc: u32) -> Option<(UserId, Option<BenutzerAudio>)> {
+        let zustand = self.zustand();
+        let user_id = zustand.zuordnung.user_von_ssrc(ssrc)?;
+        Some((user_id, zustand.einstellungen.users.get(&user_id).copied()))
+    }
+
+    /// Benutzer hinter einer SSRC im aktuellen Kanal
+    pub fn user_von_ssrc(&self, ssrc: u32) -> Option<UserId> {
+        self.zustand().zuordnung.user_von_ssrc(ssrc)
+    }
+
+    /// Wirksame Einstellungen aller gespeicherten und aller zugeordneten Benutzer
+    ///
+    /// Benutzer ohne gespeicherte Einstellung erscheinen mit 1.0 und laut.
+    pub fn lautstaerken(&self) -> Vec<BenutzerLautstaerke> {
+        let zustand = self.zustand();
+        let mut liste: Vec<BenutzerLautstaerke> = zustand
+            .einstellungen
+            .users
+            .iter()
+            .map(|(user_id, audio)| BenutzerLautstaerke {
+                user_id: *user_id,
+                ssrc: zustand.zuordnung.ssrc_von_user(user_id),
+                gain: audio.gain,
+                muted: audio.muted,
+            })
+            .collect();
+        liste.extend(
+            zustand
+                .zuordnung
+                .eintraege()
+                .filter(|(_, user_id)| !zustand.einstellungen.users.contains_key(user_id))
+                .map(|(ssrc, user_id)| BenutzerLautstaerke {
+                    user_id,
+                    ssrc: Some(ssrc),
+                    gain: 1.0,
+                    muted: false,
+                }),
+        );
+        liste.sort_by_key(|l| l.user_id.0);
+        liste
+    }
+
+    /// Vermerkt, dass ein Benutzer gehoert wurde (nur fuer vorhandene Eintraege)
+    pub fn gesehen(&self, user_id: &UserId, jetzt: u64) {
+        if let Some(audio) = self.zustand().einstellungen.users.get_mut(user_id) {
+            audio.last_seen = jetzt;
+        }
+    }
+}
+
+/// Aktuelle Zeit in Unix-Sekunden
+pub fn jetzt_unix() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uuid::Uuid;
+
+    const TAG: u64 = SEKUNDEN_PRO_TAG;
+
+    fn user(n: u128) -> UserId {
+        UserId(Uuid::from_u128(n))
+    }
+
+    #[test]
+    fn veraltete_eintraege_werden_entfernt() {
+        let pegel = BenutzerPegel::neu();
+        pegel.lautstaerke_setzen(user(1), 0.4, 0);
+        pegel.stumm_setzen(user(2), true, 50 * TAG);
+
+        assert_eq!(pegel.bereinigen(90 * TAG), 0);
+        assert_eq!(pegel.bereinigen(91 * TAG), 1);
+        let einstellungen = pegel.einstellungen();
+        assert!(!einstellungen.users.contains_key(&user(1)));
+        assert!(einstellungen.users[&user(2)].muted);
+
+        // Gehoert werden verlaengert die Aufbewahrung
+        pegel.gesehen(&user(2), 130 * TAG);
+        assert_eq!(pegel.bereinigen(200 * TAG), 0);
+    }
+
+    #[test]
+    fn laden_bereinigt_mit_eigener_aufbewahrung() {
+        let mut einstellungen = BenutzerAudioEinstellungen {
+            retention_days: 7,
+            ..Default::default()
+        };
+        einstellungen.users.insert(
+            user(1),
+            BenutzerAudio {
+                gain: 0.5,
+                muted: false,
+                last_seen: 0,
+            },
+        );
+        einstellungen.users.insert(
+            user(2),
+            BenutzerAudio {
+                gain: 1.5,
+                muted: false,
+                last_seen: 5 * TAG,
+            },
+        );
+
+        let pegel = BenutzerPegel::neu();
+        assert_eq!(pegel.laden(einstellungen, 10 * TAG), 1);
+        assert_eq!(pegel.einstellungen().users.len(), 1);
+    }
+
+    #[test]
+    fn zuruecksetzen_einzeln_und_alle() {
+        let pegel = BenutzerPegel::neu();
+        pegel.lautstaerke_setzen(user(1), 5.0, 0);
+        pegel.lautstaerke_setzen(user(2), 0.3, 0);
+        assert_eq!(pegel.einstellungen().users[&user(1)].gain, MAX_GAIN);
+
+        pegel.zuruecksetzen(Some(&user(1)));
+        assert_eq!(pegel.einstellungen().users.len(), 1);
+        pegel.zuruecksetzen(None);
+        assert!(pegel.einstellungen().users.is_empty());
+    }
+
+    #[test]
+    fn lautstaerken_mit_aktueller_ssrc() {
+        use speakeasy_core::types::ChannelId;
+        use speakeasy_protocol::control::{ChannelJoinResponse, ClientInfo, ControlPayload};
+
+        let mitglied = |user_id: UserId, ssrc: u32| ClientInfo {
+            user_id,
+            username: String::new(),
+            display_name: String::new(),
+            channel_id: None,
+            server_groups: vec![],
+            is_muted: false,
+            is_deafened: false,
+            is_input_muted: false,
+            ssrc: Some(ssrc),
+            listen_only: false,
+            soundboard: false,
+        };
+        let pegel = BenutzerPegel::neu();
+        pegel.lautstaerke_setzen(user(1), 0.5, 0);
+        pegel.lautstaerke_setzen(user(3), 1.5, 0);
+
+        let mut zuordnung = SsrcZuordnung::neu();
+        zuordnung.anwenden(&ControlPayload::ChannelJoinResponse(ChannelJoinResponse {
+            channel_id: ChannelId::new(),
+            clients: vec![mitglied(user(1), 11), mitglied(user(2), 22)],
+            member_count: 3,
+            members_partial: false,
+            listen_only: false,
+            speaking: Vec::new(),
+            slow_mode_secs: 0,
+        }));
+        pegel.zuordnung_setzen(&zuordnung);
+        assert_eq!(pegel.user_von_ssrc(22), Some(user(2)));
+
+        let liste = pegel.lautstaerken();
+        assert_eq!(liste.len(), 3);
+        assert_eq!((liste[0].ssrc, liste[0].gain), (Some(11), 0.5));
+        assert_eq!((liste[1].ssrc, liste[1].gain), (Some(22), 1.0));
+        assert_eq!((liste[2].ssrc, liste[2].gain), (None, 1.5));
+    }
+
+    #[test]
+    fn json_mit_benutzer_ids_als_schluessel() {
+        let pegel = BenutzerPegel::neu();
+        pegel.lautstaerke_setzen(user(1), 0.4, 42);
+        let json = serde_json::to_string(&pegel.einstellungen()).unwrap();
+        assert!(json.contains("\"retentionDays\":90"), "{json}");
+        assert!(json.contains("\"lastSeen\":42"), "{json}");
+
+        let zurueck: BenutzerAudioEinstellungen = serde_json::from_str(&json).unwrap();
+        assert_eq!(zurueck, pegel.einstellungen());
+        let leer: BenutzerAudioEinstellungen = serde_json::from_str("{}").unwrap();
+        assert_eq!(leer.retention_days, STANDARD_AUFBEWAHRUNG_TAGE);
+    }
+}
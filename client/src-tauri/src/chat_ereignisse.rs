@@ -0,0 +1,297 @@
+//! Chat-Ereignisse des Servers an die Oberflaeche
+//!
+//! Der Server schickt Chat-, Presence- und Sprech-Ereignisse unaufgefordert;
+//! [`ServerConnection`] reicht sie ueber einen Kanal heraus. Diese Schleife
+//! holt sie zwischen den Anfragen ab und meldet jedes als Tauri-Event an die
+//! Webview, Beitritte und Abgaenge im eigenen Kanal zusaetzlich mit Sound.
+//! Nebenbei misst sie per Ping die Laufzeit fuer die Verbindungsdiagnose.
+//!
+//! [`ServerConnection`]: crate::connection::ServerConnection
+
+use std::time::Duration;
+
+use serde::Serialize;
+use speakeasy_core::types::ChannelId;
+use speakeasy_protocol::control::{ClientInfo, ControlPayload};
+use speakeasy_protocol::namen::NamensCache;
+use tauri::{AppHandle, Emitter, Manager};
+use tokio::sync::mpsc::{self, error::TryRecvError};
+use tracing::debug;
+
+use crate::commands::{chat_nachricht_aus, event_sound_abspielen, ChatMessage};
+use crate::event_sounds::{PraesenzEreignis, SoundEreignis};
+use crate::state::AppState;
+
+/// Tauri-Event fuer eine neue Nachricht (Nutzdaten: [`ChatMessage`])
+pub const NACHRICHT_EREIGNIS: &str = "chat_message";
+/// Tauri-Event fuer eine bearbeitete Nachricht
+pub const BEARBEITET_EREIGNIS: &str = "chat_message_edited";
+/// Tauri-Event fuer eine geloeschte Nachricht
+pub const GELOESCHT_EREIGNIS: &str = "chat_message_deleted";
+/// Tauri-Event fuer geaenderte Kanal-Einstellungen
+pub const KANAL_GEAENDERT_EREIGNIS: &str = "channel_edited";
+/// Tauri-Event fuer An-/Abmeldung, Kanalwechsel oder Umbenennung eines Benutzers
+pub const PRAESENZ_EREIGNIS: &str = "presence_changed";
+/// Tauri-Event fuer eine erhaltene Kanal-Einladung (Nutzdaten: `ChannelInviteEvent`)
+pub const EINLADUNG_EREIGNIS: &str = "channel_invite";
+/// Tauri-Event fuer den Ausgang einer eigenen Einladung
+pub const EINLADUNG_ERGEBNIS_EREIGNIS: &str = "channel_invite_result";
+/// Tauri-Event fuer eine Beitrittsanfrage an den eigenen Kanal
+pub const ANKLOPFEN_EREIGNIS: &str = "channel_knock";
+/// Tauri-Event fuer den Ausgang einer Beitrittsanfrage
+pub const ANKLOPFEN_ERGEBNIS_EREIGNIS: &str = "channel_knock_result";
+/// Tauri-Event fuer einen angekuendigten Kick oder Server-Stopp (Nutzdaten:
+/// `PendingDisconnectNotice`, erneut bei jedem Countdown-Meldepunkt)
+pub const TRENNUNG_ANGEKUENDIGT_EREIGNIS: &str = "pending_disconnect";
+/// Tauri-Event fuer eine abgebrochene Ankuendigung
+pub const TRENNUNG_ABGEBROCHEN_EREIGNIS: &str = "pending_disconnect_cancelled";
+
+/// Tauri-Event fuer einen Sprechwechsel eines Kanalmitglieds
+pub const SPRECHER_EREIGNIS: &str = "client_speaking";
+
+/// Tauri-Event fuer eine gescheiterte Session-Verlaengerung (Nutzdaten:
+/// [`VerlaengerungGescheitert`]); die Oberflaeche fordert neu zur Anmeldung auf
+pub const VERLAENGERUNG_GESCHEITERT_EREIGNIS: &str = "session_renewal_failed";
+
+/// Abstand, in dem zwischen Anfragen auf Ereignisse geprueft wird
+const ABHOL_INTERVALL: Duration = Duration::from_millis(250);
+
+/// Nutzdaten von [`BEARBEITET_EREIGNIS`]
+#[derive(Debug, Clone, Serialize)]
+pub struct NachrichtBearbeitet {
+    pub id: String,
+    pub channel_id: String,
+    pub content: String,
+    pub edited_at: String,
+}
+
+/// Nutzdaten von [`GELOESCHT_EREIGNIS`]
+#[derive(Debug, Clone, Serialize)]
+pub struct NachrichtGeloescht {
+    pub id: String,
+    pub channel_id: String,
+}
+
+/// Nutzdaten von [`KANAL_GEAENDERT_EREIGNIS`]
+#[derive(Debug, Clone, Serialize)]
+pub struct KanalGeaendert {
+    pub channel_id: String,
+    /// Mindestabstand zwischen zwei Nachrichten (Sekunden, 0 = aus)
+    pub slow_mode_secs: u32,
+    /// Frist fuer das Aendern eigener Nachrichten (Sekunden, 0 = unbegrenzt)
+    pub edit_window_secs: u32,
+}
+
+/// Nutzdaten von [`SPRECHER_EREIGNIS`]
+#[derive(Debug, Clone, Serialize)]
+pub struct SprecherGeaendert {
+    pub user_id: String,
+    pub channel_id: String,
+    pub speaking: bool,
+}
+
+/// Nutzdaten von [`VERLAENGERUNG_GESCHEITERT_EREIGNIS`]
+#[derive(Debug, Clone, Serialize)]
+pub struct VerlaengerungGescheitert {
+    pub message: String,
+}
+
+/// Art einer Presence-Aenderung
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PraesenzArt {
+    Connected,
+    Disconnected,
+    Joined,
+    Left,
+    /// Anzeigename geaendert (`username` ist der neue Name)
+    Updated,
+}
+
+/// Nutzdaten von [`PRAESENZ_EREIGNIS`]
+#[derive(Debug, Clone, Serialize)]
+pub struct PraesenzGeaendert {
+    pub kind: PraesenzArt,
+    pub user_id: String,
+    pub username: String,
+    /// Betroffener Kanal (nur bei `joined`/`left`)
+    pub channel_id: Option<String>,
+}
+
+impl PraesenzGeaendert {
+    fn neu(kind: PraesenzArt, client: &ClientInfo, kanal: Option<ChannelId>) -> Self {
+        Self {
+            kind,
+            user_id: client.user_id.inner().to_string(),
+            username: client.display_name.clone(),
+            channel_id: kanal.map(|k| k.inner().to_string()),
+        }
+    }
+}
+
+/// Startet die Weiterleitung fuer eine Verbindung
+///
+/// Endet von selbst, sobald die Verbindung (und mit ihr der Sender von
+/// `ereignisse`) verworfen wird.
+pub fn starten(app: AppHandle, mut ereignisse: mpsc::UnboundedReceiver<ControlPayload>) {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(ABHOL_INTERVALL);
+        loop {
+            ticker.tick().await;
+            // Laeuft gerade eine Anfrage, liest sie die Ereignisse mit
+            if let Ok(mut tcp) = app.state::<AppState>().tcp.try_lock() {
+                if let Some(conn) = tcp.as_mut() {
+                    if let Err(e) = conn.ereignisse_abholen().await {
+                        debug!("Ereignisse konnten nicht gelesen werden: {}", e);
+                    }
+                    // RTT der Control-Verbindung fuer die Diagnose
+                    if let Err(e) = conn.ping_senden_falls_faellig().await {
+                        debug!("Ping konnte nicht gesendet werden: {}", e);
+                    }
+                }
+            }
+            loop {
+                match ereignisse.try_recv() {
+                    Ok(payload) => {
+                        if let Some(sound) = praesenz_sound(&app, &payload) {
+                            event_sound_abspielen(&app.state::<AppState>(), sound).await;
+                        }
+                        melden(&app, payload)
+                    }
+                    Err(TryRecvError::Empty) => break,
+                    Err(TryRecvError::Disconnected) => return,
+                }
+            }
+        }
+    });
+}
+
+/// Join-/Leave-Sound fuer einen Beitritt oder Abgang im eigenen Kanal
+fn praesenz_sound(app: &AppHandle, payload: &ControlPayload) -> Option<SoundEreignis> {
+    let ereignis = match payload {
+        ControlPayload::ClientJoinedChannel(ereignis) => PraesenzEreignis::ClientJoinedChannel {
+            user_id: ereignis.client.user_id.inner().to_string(),
+            channel_id: ereignis.channel_id.inner().to_string(),
+        },
+        ControlPayload::ClientLeftChannel(ereignis) => PraesenzEreignis::ClientLeftChannel {
+            user_id: ereignis.client.user_id.inner().to_string(),
+            channel_id: ereignis.channel_id.inner().to_string(),
+        },
+        _ => return None,
+    };
+    let state = app.state::<AppState>();
+    let sounds = state.event_sounds.lock().ok()?;
+    sounds.praesenz_sound(&ereignis)
+}
+
+/// Meldet ein Chat-, Kanal- oder Presence-Ereignis an die Oberflaeche
+fn melden(app: &AppHandle, payload: ControlPayload) {
+    let ergebnis = match payload {
+        ControlPayload::ChatMessage(ereignis) => {
+            // Der Server nennt den Absender gleich mit
+            let nachricht = ChatMessage {
+                sender_name: ereignis.sender_name,
+                ..chat_nachricht_aus(ereignis.message, &NamensCache::neu())
+            };
+            app.emit(NACHRICHT_EREIGNIS, nachricht)
+        }
+        ControlPayload::ChatEdited(ereignis) => app.emit(
+            BEARBEITET_EREIGNIS,
+            NachrichtBearbeitet {
+                id: ereignis.message_id,
+                channel_id: ereignis.channel_id.inner().to_string(),
+                content: ereignis.content,
+                edited_at: ereignis.edited_at,
+            },
+        ),
+        ControlPayload::ChatDeleted(ereignis) => app.emit(
+            GELOESCHT_EREIGNIS,
+            NachrichtGeloescht {
+                id: ereignis.message_id,
+                channel_id: ereignis.channel_id.inner().to_string(),
+            },
+        ),
+        // Nach einer Moderations-Bereinigung: wie einzeln geloeschte
+        ControlPayload::ChatBulkDeleted(ereignis) => {
+            let channel_id = ereignis.channel_id.inner().to_string();
+            ereignis.message_ids.into_iter().try_for_each(|id| {
+                app.emit(
+                    GELOESCHT_EREIGNIS,
+                    NachrichtGeloescht {
+                        id,
+                        channel_id: channel_id.clone(),
+                    },
+                )
+            })
+        }
+        ControlPayload::ChannelEdited(ereignis) => app.emit(
+            KANAL_GEAENDERT_EREIGNIS,
+            KanalGeaendert {
+                channel_id: ereignis.channel.channel_id.inner().to_string(),
+                slow_mode_secs: ereignis.channel.slow_mode_secs,
+                edit_window_secs: ereignis.channel.edit_window_secs,
+            },
+        ),
+        ControlPayload::ClientConnected(ereignis) => app.emit(
+            PRAESENZ_EREIGNIS,
+            PraesenzGeaendert::neu(PraesenzArt::Connected, &ereignis.client, None),
+        ),
+        ControlPayload::ClientDisconnected(ereignis) => app.emit(
+            PRAESENZ_EREIGNIS,
+            PraesenzGeaendert::neu(PraesenzArt::Disconnected, &ereignis.client, None),
+        ),
+        ControlPayload::ClientJoinedChannel(ereignis) => app.emit(
+            PRAESENZ_EREIGNIS,
+            PraesenzGeaendert::neu(
+                PraesenzArt::Joined,
+                &ereignis.client,
+                Some(ereignis.channel_id),
+            ),
+        ),
+        ControlPayload::ClientLeftChannel(ereignis) => app.emit(
+            PRAESENZ_EREIGNIS,
+            PraesenzGeaendert::neu(
+                PraesenzArt::Left,
+                &ereignis.client,
+                Some(ereignis.channel_id),
+            ),
+        ),
+        ControlPayload::ClientUpdated(ereignis) => app.emit(
+            PRAESENZ_EREIGNIS,
+            PraesenzGeaendert::neu(PraesenzArt::Updated, &ereignis.client, None),
+        ),
+        ControlPayload::ClientSpeaking(ereignis) => app.emit(
+            SPRECHER_EREIGNIS,
+            SprecherGeaendert {
+                user_id: ereignis.user_id.inner().to_string(),
+                channel_id: ereignis.channel_id.inner().to_string(),
+                speaking: ereignis.speaking,
+            },
+        ),
+        ControlPayload::ChannelInviteReceived(ereignis) => app.emit(EINLADUNG_EREIGNIS, ereignis),
+        ControlPayload::ChannelInviteResult(ereignis) => {
+            app.emit(EINLADUNG_ERGEBNIS_EREIGNIS, ereignis)
+        }
+        ControlPayload::ChannelKnockReceived(ereignis) => app.emit(ANKLOPFEN_EREIGNIS, ereignis),
+        ControlPayload::ChannelKnockResult(ereignis) => {
+            app.emit(ANKLOPFEN_ERGEBNIS_EREIGNIS, ereignis)
+        }
+        ControlPayload::PendingDisconnectNotice(meldung) => {
+            app.emit(TRENNUNG_ANGEKUENDIGT_EREIGNIS, meldung)
+        }
+        ControlPayload::PendingDisconnectCancelled(ereignis) => {
+            app.emit(TRENNUNG_ABGEBROCHEN_EREIGNIS, ereignis)
+        }
+        // Als Fehler reicht die Verbindung nur die gescheiterte Verlaengerung heraus
+        ControlPayload::Error(fehler) => app.emit(
+            VERLAENGERUNG_GESCHEITERT_EREIGNIS,
+            VerlaengerungGescheitert {
+                message: fehler.message,
+            },
+        ),
+        _ => return,
+    };
+    if let Err(e) = ergebnis {
+        debug!("Chat-Event konnte nicht gesendet werden: {}", e);
+    }
+}
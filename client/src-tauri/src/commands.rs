@@ -1,16 +1,43 @@
 use serde::{Deserialize, Serialize};
-use tauri::State;
-use tracing::{debug, info, warn};
-
-use speakeasy_core::types::ChannelId;
+use tauri::{Emitter, State};
+use tracing::{debug, error, info, warn};
+
+use speakeasy_audio::codec::CodecStatistik;
+use speakeasy_audio::engine::AudioEngineConfig;
+use speakeasy_audio::hardware_stumm::STANDARD_NULL_DAUER;
+use speakeasy_audio::ptt::PttMode;
+use speakeasy_core::types::{ChannelId, UserId};
 use speakeasy_protocol::control::{
-    ChannelCreateRequest, ChannelDeleteRequest, ChannelEditRequest, ChatDeleteRequest,
-    ChatEditRequest, ChatHistoryRequest, ChatSendRequest, ControlPayload, FileUploadRequest,
-    NicknameChangeRequest, PasswordChangeRequest, SetAwayRequest,
+    ChannelCreateRequest, ChannelDeleteRequest, ChannelEditRequest, ChannelInviteResponse,
+    ChannelKnockResponse, ChannelKnockResultEvent, ChatDeleteRequest, ChatEditRequest,
+    ChatHistoryRequest, ChatMessageInfo, ChatSearchRequest, ChatSendRequest, ControlPayload,
+    ErrorCode, FileDownloadRequest, FileUploadRequest, Motd, NicknameChangeRequest,
+    PasswordChangeRequest, SetAwayRequest,
 };
-
-use crate::connection::ServerConnection;
+use speakeasy_protocol::crypto::CryptoMode;
+use speakeasy_protocol::handshake::faehigkeit;
+use speakeasy_protocol::namen::NamensCache;
+use speakeasy_protocol::qos::{QosStatus, DSCP_EF};
+use speakeasy_protocol::socket_statistik::SocketZaehler;
+use speakeasy_protocol::voice::{hello_nonce_dekodieren, AudioCodec};
+use std::collections::{HashMap, HashSet};
+
+use crate::benutzer_audio::{jetzt_unix, BenutzerAudioEinstellungen, BenutzerLautstaerke};
+use crate::chat_ereignisse;
+use crate::connection::{ConnectionError, ServerConnection};
+use crate::datei_download;
+use crate::datei_upload;
+use crate::entwuerfe::{EntwurfsEinstellungen, ENTWUERFE_EREIGNIS};
+use crate::event_sounds::{EventSoundSettings, SoundEreignis};
+use crate::ptt::{self, PttZustand};
+use crate::server_ping::{self, LatenzMessung};
 use crate::state::AppState;
+use crate::validation;
+use crate::voice::{VoiceClient, VoiceEreignis, VoiceStartFehler, STANDARD_DTX_KEEPALIVE};
+use crate::voice_debug::{self, VoiceDebugZustand, VoiceSitzung};
+use crate::voice_jitter::{JitterEinstellung, STANDARD_BUDGET_MS};
+use crate::voice_stats::VerbindungsStatistik;
+use crate::voice_trace::{self, TraceBericht, TraceZusammenfassung};
 
 // --- Datentypen ---
 
@@ -39,6 +66,16 @@ pub struct ClientInfo {
     pub is_muted: bool,
     pub is_deafened: bool,
     pub is_self: bool,
+    /// Nur-Zuhoerer (Listener-Badge)
+    pub is_listener: bool,
+    /// Spricht gerade (laut Server, auch ohne eigene Pakete)
+    pub is_speaking: bool,
+    /// Letzte Sprechaktivitaet (Unix-Millisekunden), fuer die Sortierung
+    pub last_spoke_at: Option<u64>,
+    /// Durch die Notfall-Stummschaltung des Kanals stumm (Mikrofon ausgegraut)
+    pub is_emergency_muted: bool,
+    /// Serverseitige Soundboard-Wiedergabe statt eines Benutzers
+    pub is_soundboard: bool,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -49,6 +86,19 @@ pub struct ChannelInfo {
     pub parent_id: Option<String>,
     pub clients: Vec<ClientInfo>,
     pub max_clients: u32,
+    /// Unterkanaele vorhanden, die noch nicht geladen sind (`expand_channel`)
+    pub has_children: bool,
+    /// Anzahl direkter Unterkanaele
+    pub child_count: u32,
+    /// Notfall-Stummschaltung aktiv (Banner)
+    pub emergency_muted: bool,
+    /// Mindestabstand zwischen zwei Chat-Nachrichten (Sekunden, 0 = aus)
+    pub slow_mode_secs: u32,
+    /// So lange nach dem Senden sind eigene Nachrichten aenderbar
+    /// (Sekunden, 0 = unbegrenzt)
+    pub edit_window_secs: u32,
+    /// Beitritt nur nach Freigabe (`knock_channel`)
+    pub join_by_approval: bool,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -60,6 +110,8 @@ pub struct ServerInfo {
     pub online_clients: u32,
     pub uptime_secs: u64,
     pub channels: Vec<ChannelInfo>,
+    /// Nachricht des Tages (Markdown-Teilmenge, `None` = keine gesetzt)
+    pub motd: Option<Motd>,
 }
 
 // --- Ergebnis-Typen ---
@@ -70,19 +122,103 @@ pub struct ConnectResult {
     pub success: bool,
     /// Ob der Benutzer sein Passwort zwingend aendern muss
     pub must_change_password: bool,
+    /// Willkommensnachricht des Servers (falls gesetzt)
+    pub welcome_message: Option<String>,
+    /// Nachricht des Tages (falls gesetzt)
+    pub motd: Option<Motd>,
+    /// Hoechstlaenge einer Chat-Nachricht auf diesem Server (Zeichen)
+    pub max_message_chars: usize,
+}
+
+/// Fehler von `connect_to_server`
+///
+/// Unvertraegliche Protokollversionen kommen strukturiert an, damit die
+/// Oberflaeche beide Versionen und einen Hinweis anzeigen kann.
+#[derive(Debug, Serialize, Clone)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ConnectError {
+    /// Client und Server sprechen unvertraegliche Protokollversionen
+    Incompatible {
+        client_version: String,
+        /// `None` = Server aelter als der Versionsabgleich
+        server_version: Option<String>,
+        suggestion: String,
+    },
+    /// Alle anderen Fehler
+    Failed { message: String },
+}
+
+impl From<String> for ConnectError {
+    fn from(message: String) -> Self {
+        ConnectError::Failed { message }
+    }
+}
+
+impl From<validation::ValidationError> for ConnectError {
+    fn from(e: validation::ValidationError) -> Self {
+        e.to_string().into()
+    }
+}
+
+/// Fehler von `send_message`
+///
+/// Eine vom Langsam-Modus abgelehnte Nachricht kommt mit der Wartezeit an,
+/// damit die Oberflaeche einen Countdown anzeigen kann.
+#[derive(Debug, Serialize, Clone)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SendMessageError {
+    /// Zu frueh gesendet, erneut erlaubt nach `retry_after_secs`
+    RateLimited {
+        retry_after_secs: u64,
+        message: String,
+    },
+    /// Alle anderen Fehler
+    Failed { message: String },
 }
 
+impl From<String> for SendMessageError {
+    fn from(message: String) -> Self {
+        SendMessageError::Failed { message }
+    }
+}
+
+impl From<validation::ValidationError> for SendMessageError {
+    fn from(e: validation::ValidationError) -> Self {
+        e.to_string().into()
+    }
+}
+
+/// Tauri-Event nach dem Verbinden: Funktionen, die der Server nicht anbietet
+pub const FAEHIGKEITEN_EREIGNIS: &str = "capabilities_degraded";
+
+/// Nutzdaten von [`FAEHIGKEITEN_EREIGNIS`]
+#[derive(Debug, Serialize, Clone)]
+pub struct FehlendeFaehigkeiten {
+    /// Leer = alle Funktionen verfuegbar
+    pub missing: Vec<String>,
+}
+
+/// Faehigkeiten, ohne die die Oberflaeche Funktionen ausblenden muss
+const BENOETIGTE_FAEHIGKEITEN: &[&str] = &[
+    faehigkeit::CHAT,
+    faehigkeit::CHAT_EREIGNISSE,
+    faehigkeit::BENACHRICHTIGUNGEN,
+];
+
 // --- Commands ---
 
 /// Verbindet sich mit einem Speakeasy-Server
 #[tauri::command]
 pub async fn connect_to_server(
+    app: tauri::AppHandle,
     state: State<'_, AppState>,
     address: String,
     port: u16,
     username: String,
     password: Option<String>,
-) -> Result<ConnectResult, String> {
+) -> Result<ConnectResult, ConnectError> {
+    validation::verbindung(&address, port, &username, password.as_deref())?;
+
     info!(
         "Verbinde mit {}:{} als '{}' (Passwort: {})",
         address,
@@ -92,10 +228,39 @@ pub async fn connect_to_server(
     );
 
     // TCP-Verbindung aufbauen
-    let mut server_conn = ServerConnection::connect(&address, port)
+    let control_dscp = state.qos.lock().map_err(|e| e.to_string())?.control_dscp;
+    let mut server_conn = ServerConnection::connect(&address, port, control_dscp)
         .await
         .map_err(|e| format!("Verbindungsfehler: {}", e))?;
 
+    // Protokoll abgleichen, bevor der Login an Serde-Fehlern scheitert
+    let kompatibel = server_conn
+        .handshake(BENOETIGTE_FAEHIGKEITEN)
+        .await
+        .map_err(|e| match e {
+            ConnectionError::Inkompatibel(e) => ConnectError::Incompatible {
+                client_version: e.client.to_string(),
+                server_version: e.server.map(|v| v.to_string()),
+                suggestion: e.hinweis,
+            },
+            e => format!("Verbindungsfehler: {}", e).into(),
+        })?;
+    if !kompatibel.fehlend.is_empty() {
+        warn!(
+            "Server bietet nicht alle Funktionen an, eingeschraenkt: {:?}",
+            kompatibel.fehlend
+        );
+    }
+    server_conn.set_benutzer_pegel(std::sync::Arc::clone(&state.benutzer_pegel));
+    server_conn.set_ziel_bitrate(std::sync::Arc::clone(&state.ziel_bitrate));
+    server_conn.set_voice_schluessel(std::sync::Arc::clone(&state.voice_schluessel));
+    let (ereignis_tx, ereignis_rx) = tokio::sync::mpsc::unbounded_channel();
+    server_conn.set_ereignisse(ereignis_tx);
+    let veraltet = state.benutzer_pegel.bereinigen(jetzt_unix());
+    if veraltet > 0 {
+        debug!("{} veraltete Benutzer-Lautstaerken entfernt", veraltet);
+    }
+
     // Login durchfuehren
     let pwd = password.as_deref().unwrap_or("");
     let login_resp = server_conn
@@ -104,6 +269,9 @@ pub async fn connect_to_server(
         .map_err(|e| format!("Login fehlgeschlagen: {}", e))?;
 
     let must_change_password = login_resp.must_change_password;
+    let welcome_message = login_resp.welcome_message;
+    let motd = login_resp.motd;
+    let max_message_chars = server_conn.max_nachricht_zeichen();
 
     // Metadaten im sync ConnectionState speichern
     {
@@ -113,17 +281,35 @@ pub async fn connect_to_server(
         conn.server_port = Some(port);
         conn.username = Some(username);
         conn.force_password_change = must_change_password;
+        conn.max_message_chars = Some(max_message_chars);
     }
+    state
+        .aktivitaet
+        .lock()
+        .map_err(|e| e.to_string())?
+        .zuruecksetzen();
 
     // Echte TCP-Verbindung im async Mutex speichern
     {
         let mut tcp = state.tcp.lock().await;
         *tcp = Some(server_conn);
     }
+    if let Err(e) = app.emit(
+        FAEHIGKEITEN_EREIGNIS,
+        FehlendeFaehigkeiten {
+            missing: kompatibel.fehlend,
+        },
+    ) {
+        debug!("Faehigkeiten-Event konnte nicht gesendet werden: {}", e);
+    }
+    chat_ereignisse::starten(app, ereignis_rx);
 
     Ok(ConnectResult {
         success: true,
         must_change_password,
+        welcome_message,
+        motd,
+        max_message_chars,
     })
 }
 
@@ -187,19 +373,52 @@ pub async fn disconnect(state: State<'_, AppState>) -> Result<(), String> {
         conn.server_address = None;
         conn.server_port = None;
         conn.current_channel = None;
+        conn.max_message_chars = None;
+    }
+    if let Ok(mut sounds) = state.event_sounds.lock() {
+        sounds.kanal_verlassen();
     }
 
     Ok(())
 }
 
 /// Tritt einem Kanal bei und startet die Voice-Pipeline
+///
+/// Mit `listen_only` wird nur zugehoert: das Mikrofon bleibt geschlossen.
+/// Der Server kann den Modus auch erzwingen (fehlendes `b_voice_transmit`).
 #[tauri::command]
 pub async fn join_channel(
+    app: tauri::AppHandle,
     state: State<'_, AppState>,
     channel_id: String,
+    listen_only: Option<bool>,
 ) -> Result<(), String> {
+    validation::kanal_id(&channel_id)?;
     debug!("Trete Kanal {} bei", channel_id);
+    kanal_betreten(
+        app,
+        &state,
+        Beitritt::Kanal(channel_id),
+        listen_only.unwrap_or(false),
+    )
+    .await
+}
+
+/// Weg in einen Kanal
+enum Beitritt {
+    /// Direkter Beitritt ueber die Kanal-ID
+    Kanal(String),
+    /// Annahme der Einladung mit dieser ID
+    Einladung(String),
+}
 
+/// Tritt einem Kanal bei und startet Voice-Init und Voice-Pipeline
+async fn kanal_betreten(
+    app: tauri::AppHandle,
+    state: &AppState,
+    beitritt: Beitritt,
+    listen_only: bool,
+) -> Result<(), String> {
     let server_address: String;
     {
         let conn = state.connection.lock().map_err(|e| e.to_string())?;
@@ -212,27 +431,61 @@ pub async fn join_channel(
             .ok_or_else(|| "Keine Server-Adresse bekannt".to_string())?;
     }
 
+    // Ohne nutzbares Opus wird PCMU angefragt; ob der Fallback erlaubt ist,
+    // entscheidet der Server
+    let opus_config = crate::voice::standard_opus_config();
+    let bevorzugter_codec = VoiceClient::bevorzugter_codec(&opus_config);
+
     // 1. Kanal-Beitritt ueber TCP-Verbindung
     // 2. Voice-Init: UDP Port Negotiation
-    let voice_ready = {
+    let (voice_ready, nur_hoeren, channel_id, selbst) = {
         let mut tcp = state.tcp.lock().await;
         let conn = tcp
             .as_mut()
             .ok_or_else(|| "Keine TCP-Verbindung vorhanden".to_string())?;
 
-        conn.join_channel(&channel_id)
-            .await
-            .map_err(|e| format!("Kanal-Beitritt fehlgeschlagen: {}", e))?;
+        let (channel_id, nur_hoeren) = match beitritt {
+            Beitritt::Kanal(channel_id) => {
+                let nur_hoeren = conn
+                    .join_channel(&channel_id, listen_only)
+                    .await
+                    .map_err(|e| format!("Kanal-Beitritt fehlgeschlagen: {}", e))?;
+                (channel_id, nur_hoeren)
+            }
+            Beitritt::Einladung(invite_id) => conn
+                .accept_invite(&invite_id, listen_only)
+                .await
+                .map_err(|e| format!("Einladung konnte nicht angenommen werden: {}", e))?,
+        };
 
-        // Voice-Init senden (Port 0 = wird nach Socket-Bind aktualisiert)
-        // Wir senden erstmal Port 0, der Server kennt unsere IP aus der TCP-Verbindung
-        conn.voice_init(0)
-            .await
-            .map_err(|e| format!("Voice-Init fehlgeschlagen: {}", e))?
+        // Voice-Init senden: der Port ist nur informativ, den UDP-Endpunkt
+        // lernt der Server aus dem Hello nach dem Start der Pipeline
+        match conn
+            .voice_init(
+                0,
+                bevorzugter_codec,
+                Some(crate::voice::standard_codec_anfrage()),
+            )
+            .await {
+            Ok(ready) => {
+                let selbst = conn.user_id().unwrap_or_default().to_string();
+                (ready, nur_hoeren, channel_id, selbst)
+            }
+            Err(e) => {
+                // Sonst bliebe der Benutzer ohne Voice im Kanal stehen
+                let meldung = format!("Voice-Init fehlgeschlagen: {}", e);
+                error!("{}", meldung);
+                kanalbeitritt_zuruecknehmen(conn, &channel_id, &meldung).await;
+                drop(tcp);
+                let mut conn = state.connection.lock().map_err(|e| e.to_string())?;
+                conn.current_channel = None;
+                return Err(meldung);
+            }
+        }
     };
 
     // 3. Voice-Pipeline starten
-    {
+    let start_fehler = {
         let server_ip = if voice_ready.server_ip.is_empty() {
             server_address.clone()
         } else {
@@ -252,23 +505,147 @@ pub async fn join_channel(
             client.stop().await;
         }
 
-        let mut client = crate::voice::VoiceClient::new();
-        if let Err(e) = client.start(server_udp_addr, voice_ready.ssrc).await {
-            tracing::warn!("Voice-Pipeline (Audio-Hardware) konnte nicht gestartet werden: {}", e);
+        let mut client = VoiceClient::new();
+        client.set_opus_config(opus_config);
+        let ducking = state
+            .event_sounds
+            .lock()
+            .map(|s| s.einstellungen().ducking_faktor())
+            .unwrap_or_default();
+        client.set_event_ducking(ducking);
+        client.set_dscp(state.qos.lock().map_err(|e| e.to_string())?.voice_dscp);
+        client.set_trace(std::sync::Arc::clone(&state.voice_trace));
+        client.set_benutzer_pegel(std::sync::Arc::clone(&state.benutzer_pegel));
+        client.set_ziel_bitrate(std::sync::Arc::clone(&state.ziel_bitrate));
+        client.set_hardware_stumm(
+            state
+                .hardware_stumm
+                .lock()
+                .map_err(|e| e.to_string())?
+                .null_dauer(),
+        );
+        client.set_jitter(
+            state
+                .audio
+                .lock()
+                .map_err(|e| e.to_string())?
+                .full_settings
+                .as_ref()
+                .map(|s| s.jitter.einstellung())
+                .unwrap_or_default(),
+        );
+        client.set_dtx(
+            state
+                .audio
+                .lock()
+                .map_err(|e| e.to_string())?
+                .full_settings
+                .as_ref()
+                .map_or(Some(STANDARD_DTX_KEEPALIVE), |s| s.codec.dtx_keepalive()),
+        );
+        client.set_echo_unterdrueckung(
+            state
+                .audio
+                .lock()
+                .map_err(|e| e.to_string())?
+                .full_settings
+                .as_ref()
+                .and_then(|s| s.dsp.echo_cancellation.voice()),
+        );
+        client.set_ptt_freigabe(state.ptt.freigabe());
+        client.set_sprech_melder(std::sync::Arc::new(move |spricht| {
+            ptt::sprechen_melden(&app, spricht)
+        }));
+        client.set_listen_only(nur_hoeren);
+        client.set_sitzung(VoiceSitzung::aus_voice_ready(&voice_ready));
+        client.set_hello_nonce(
+            voice_ready
+                .hello_nonce
+                .as_deref()
+                .and_then(hello_nonce_dekodieren),
+        );
+        // Bei `dtls` schuetzt der Transport, nur `e2e` verschluesselt die Nutzdaten
+        client.set_verschluesselung(
+            (voice_ready.crypto_mode.parse::<CryptoMode>() == Ok(CryptoMode::E2E))
+                .then(|| std::sync::Arc::clone(&state.voice_schluessel)),
+        );
+        let codec = AudioCodec::aus_name(&voice_ready.codec).unwrap_or_default();
+        match client
+            .start(
+                server_udp_addr,
+                voice_ready.ssrc,
+                codec,
+                voice_ready.codec_negotiation.as_ref(),
+            )
+            .await
+        {
+            Err(e @ VoiceStartFehler::CodecNichtVerfuegbar { .. }) => {
+                *voice = None;
+                Some(e)
+            }
+            Err(e) => {
+                warn!(
+                    "Voice-Pipeline (Audio-Hardware) konnte nicht gestartet werden: {}",
+                    e
+                );
+                *voice = Some(client);
+                None
+            }
+            Ok(()) => {
+                *voice = Some(client);
+                None
+            }
         }
+    };
 
-        *voice = Some(client);
+    // Ohne Codec waere der Benutzer stumm im Kanal: Beitritt zuruecknehmen
+    if let Some(fehler) = start_fehler {
+        error!("Voice-Pipeline konnte nicht gestartet werden: {}", fehler);
+        {
+            let mut tcp = state.tcp.lock().await;
+            if let Some(ref mut conn) = *tcp {
+                kanalbeitritt_zuruecknehmen(conn, &channel_id, &fehler.to_string()).await;
+            }
+        }
+        let mut conn = state.connection.lock().map_err(|e| e.to_string())?;
+        conn.current_channel = None;
+        return Err(fehler.benutzer_meldung());
     }
 
     // Metadaten aktualisieren
     {
         let mut conn = state.connection.lock().map_err(|e| e.to_string())?;
-        conn.current_channel = Some(channel_id);
+        conn.current_channel = Some(channel_id.clone());
+    }
+    // Join/Leave-Sounds gelten ab jetzt fuer diesen Kanal (nach Karenzzeit)
+    if let Ok(mut sounds) = state.event_sounds.lock() {
+        sounds.kanal_beigetreten(channel_id, selbst, std::time::Instant::now());
     }
 
     Ok(())
 }
 
+/// Nimmt einen Kanalbeitritt zurueck, dessen Voice-Aufbau gescheitert ist
+///
+/// Fehler werden nur protokolliert; der Aufrufer meldet den urspruenglichen
+/// Grund. Antwortet der Server nicht, wird nach einem Zeitlimit aufgegeben.
+async fn kanalbeitritt_zuruecknehmen(conn: &mut ServerConnection, channel_id: &str, grund: &str) {
+    let zuruecknehmen = async {
+        if let Err(e) = conn.voice_disconnect(Some(grund.to_string())).await {
+            warn!("Voice-Disconnect fehlgeschlagen: {} (wird ignoriert)", e);
+        }
+        if let Err(e) = conn.leave_channel(channel_id).await {
+            warn!("Kanal-Verlassen fehlgeschlagen: {} (wird ignoriert)", e);
+        }
+    };
+    if tokio::time::timeout(std::time::Duration::from_secs(5), zuruecknehmen)
+        .await
+        .is_err()
+    {
+        warn!("Kanalbeitritt {} konnte nicht zurueckgenommen werden: Zeitlimit", channel_id);
+    }
+}
+
 /// Verlaesst den aktuellen Kanal und stoppt die Voice-Pipeline
 #[tauri::command]
 pub async fn leave_channel(state: State<'_, AppState>) -> Result<(), String> {
@@ -313,10 +690,108 @@ pub async fn leave_channel(state: State<'_, AppState>) -> Result<(), String> {
         let mut conn = state.connection.lock().map_err(|e| e.to_string())?;
         conn.current_channel = None;
     }
+    if let Ok(mut sounds) = state.event_sounds.lock() {
+        sounds.kanal_verlassen();
+    }
 
     Ok(())
 }
 
+/// Laedt einen verbundenen Benutzer in den eigenen Kanal ein
+///
+/// Der Ausgang kommt als `channel_invite_result`-Event.
+#[tauri::command]
+pub async fn invite_to_channel(
+    state: State<'_, AppState>,
+    channel_id: String,
+    user_id: String,
+    message: Option<String>,
+) -> Result<ChannelInviteResponse, String> {
+    validation::einladen(&channel_id, &user_id, message.as_deref())?;
+    debug!("Lade {} in Kanal {} ein", user_id, channel_id);
+
+    let mut tcp = state.tcp.lock().await;
+    let conn = tcp
+        .as_mut()
+        .ok_or_else(|| "Nicht verbunden – bitte zuerst connect_to_server aufrufen".to_string())?;
+    conn.invite_to_channel(&channel_id, &user_id, message)
+        .await
+        .map_err(|e| format!("Einladung fehlgeschlagen: {}", e))
+}
+
+/// Beantwortet eine Einladung (`channel_invite`-Event)
+///
+/// Bei Annahme wird wie bei [`join_channel`] beigetreten, Kanal-Passwort und
+/// Freigabe entfallen.
+#[tauri::command]
+pub async fn answer_channel_invite(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    invite_id: String,
+    accept: bool,
+    listen_only: Option<bool>,
+) -> Result<(), String> {
+    validation::id("Einladungs-ID", &invite_id)?;
+    if accept {
+        debug!("Nehme Einladung {} an", invite_id);
+        return kanal_betreten(
+            app,
+            &state,
+            Beitritt::Einladung(invite_id),
+            listen_only.unwrap_or(false),
+        )
+        .await;
+    }
+
+    let mut tcp = state.tcp.lock().await;
+    let conn = tcp
+        .as_mut()
+        .ok_or_else(|| "Nicht verbunden – bitte zuerst connect_to_server aufrufen".to_string())?;
+    conn.decline_invite(&invite_id)
+        .await
+        .map_err(|e| format!("Einladung konnte nicht abgelehnt werden: {}", e))
+}
+
+/// Bittet um Einlass in einen Kanal mit Freigabe
+///
+/// Der Ausgang kommt als `channel_knock_result`-Event; nach Einlass ist
+/// [`join_channel`] ohne Passwort moeglich.
+#[tauri::command]
+pub async fn knock_channel(
+    state: State<'_, AppState>,
+    channel_id: String,
+    message: Option<String>,
+) -> Result<ChannelKnockResponse, String> {
+    validation::anklopfen(&channel_id, message.as_deref())?;
+    debug!("Klopfe an Kanal {} an", channel_id);
+
+    let mut tcp = state.tcp.lock().await;
+    let conn = tcp
+        .as_mut()
+        .ok_or_else(|| "Nicht verbunden – bitte zuerst connect_to_server aufrufen".to_string())?;
+    conn.knock_channel(&channel_id, message)
+        .await
+        .map_err(|e| format!("Anklopfen fehlgeschlagen: {}", e))
+}
+
+/// Laesst einen Anfragenden ein oder lehnt ihn ab (`channel_knock`-Event)
+#[tauri::command]
+pub async fn answer_channel_knock(
+    state: State<'_, AppState>,
+    knock_id: String,
+    admit: bool,
+) -> Result<ChannelKnockResultEvent, String> {
+    validation::id("Anfrage-ID", &knock_id)?;
+
+    let mut tcp = state.tcp.lock().await;
+    let conn = tcp
+        .as_mut()
+        .ok_or_else(|| "Nicht verbunden – bitte zuerst connect_to_server aufrufen".to_string())?;
+    conn.answer_knock(&knock_id, admit)
+        .await
+        .map_err(|e| format!("Beitrittsanfrage konnte nicht beantwortet werden: {}", e))
+}
+
 /// PulseAudio/PipeWire-Quellen als zusaetzliche Geraete abrufen
 fn get_pulse_sources() -> Vec<(String, String)> {
     let output = std::process::Command::new("pactl")
@@ -469,6 +944,7 @@ pub async fn get_audio_devices() -> Result<Vec<AudioDevice>, String> {
 /// Setzt die Audio-Konfiguration
 #[tauri::command]
 pub async fn set_audio_config(config: AudioConfig) -> Result<(), String> {
+    validation::audio_config(&config)?;
     debug!(
         "Setze Audio-Konfiguration: input={:?}, output={:?}",
         config.input_device_id, config.output_device_id
@@ -491,14 +967,41 @@ pub async fn toggle_mute(state: State<'_, AppState>) -> Result<bool, String> {
     };
 
     // Voice-Client informieren
-    let voice = state.voice.lock().await;
-    if let Some(ref client) = *voice {
-        client.set_muted(muted);
+    {
+        let voice = state.voice.lock().await;
+        if let Some(ref client) = *voice {
+            client.set_muted(muted);
+        }
     }
 
+    event_sound_abspielen(&state, SoundEreignis::MuteToggle).await;
+
     Ok(muted)
 }
 
+/// Wechselt im aktuellen Kanal zwischen Senden und Nur-Zuhoeren
+///
+/// Der Server prueft `b_voice_transmit`; erst nach seiner Zustimmung wird
+/// das Mikrofon geoeffnet bzw. geschlossen.
+#[tauri::command]
+pub async fn set_listen_only(state: State<'_, AppState>, listen_only: bool) -> Result<(), String> {
+    {
+        let mut tcp = state.tcp.lock().await;
+        let conn = tcp
+            .as_mut()
+            .ok_or_else(|| "Keine TCP-Verbindung vorhanden".to_string())?;
+        conn.set_transmit(!listen_only)
+            .await
+            .map_err(|e| format!("Moduswechsel fehlgeschlagen: {}", e))?;
+    }
+
+    let voice = state.voice.lock().await;
+    if let Some(ref client) = *voice {
+        client.set_listen_only(listen_only);
+    }
+    Ok(())
+}
+
 /// Schaltet den Ton aus/ein (deaf)
 #[tauri::command]
 pub async fn toggle_deafen(state: State<'_, AppState>) -> Result<bool, String> {
@@ -535,9 +1038,23 @@ pub struct CodecConfig {
     pub application: String,
     pub fec: bool,
     pub dtx: bool,
+    /// Abstand der Silence-Pakete in Sprechpausen bei aktivem DTX
+    #[serde(default = "standard_dtx_keepalive_ms")]
+    pub dtx_keepalive_ms: u32,
     pub channels: String,
 }
 
+fn standard_dtx_keepalive_ms() -> u32 {
+    STANDARD_DTX_KEEPALIVE.as_millis() as u32
+}
+
+impl CodecConfig {
+    /// DTX-Einstellung fuer die Voice-Pipeline (`None` = jeden Frame senden)
+    pub fn dtx_keepalive(&self) -> Option<std::time::Duration> {
+        self.dtx.then(|| std::time::Duration::from_millis(u64::from(self.dtx_keepalive_ms)))
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct NoiseGateConfig {
     pub enabled: bool,
@@ -565,6 +1082,28 @@ pub struct AgcConfig {
 pub struct EchoCancellationConfig {
     pub enabled: bool,
     pub tail_length: u32,
+    /// Fester Versatz zwischen Ausgabe und Echo im Mikrofon (ms)
+    #[serde(default = "standard_echo_versatz_ms")]
+    pub delay_offset_ms: u32,
+}
+
+fn standard_echo_versatz_ms() -> u32 {
+    25
+}
+
+impl EchoCancellationConfig {
+    /// Konfiguration der Echo Cancellation (Echo-Laenge und Versatz)
+    pub fn aec_config(&self) -> speakeasy_audio::dsp::echo_cancel::EchoCancelConfig {
+        speakeasy_audio::dsp::echo_cancel::EchoCancelConfig::aus_ms(
+            self.tail_length,
+            self.delay_offset_ms,
+        )
+    }
+
+    /// Echo-Unterdrueckung fuer die Voice-Pipeline (`None` = aus)
+    pub fn voice(&self) -> Option<speakeasy_audio::dsp::echo_cancel::EchoCancelConfig> {
+        self.enabled.then(|| self.aec_config())
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -591,6 +1130,37 @@ pub struct JitterConfig {
     pub min_buffer: u32,
     pub max_buffer: u32,
     pub adaptive: bool,
+    /// Hoechste hingenommene Puffer-Verzoegerung in ms, unabhaengig vom Jitter
+    #[serde(default = "standard_latenz_budget")]
+    pub latency_budget: u32,
+}
+
+fn standard_latenz_budget() -> u32 {
+    STANDARD_BUDGET_MS
+}
+
+impl JitterConfig {
+    /// Puffergroesse fuer den Empfangspfad der Voice-Pipeline
+    pub fn einstellung(&self) -> JitterEinstellung {
+        JitterEinstellung::aus_ms(self.min_buffer, self.max_buffer, self.adaptive)
+            .mit_budget_ms(self.latency_budget)
+    }
+
+    /// Voreinstellung fuer einen Modus: `low_latency` (knappes Budget, lieber
+    /// verdecken als warten) oder `stability` (grosszuegiges Budget)
+    pub fn modus(name: &str) -> Option<Self> {
+        let (min_buffer, max_buffer, latency_budget) = match name {
+            "low_latency" => (20, 120, 100),
+            "stability" => (60, 500, 400),
+            _ => return None,
+        };
+        Some(Self {
+            min_buffer,
+            max_buffer,
+            adaptive: true,
+            latency_budget,
+        })
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -629,9 +1199,177 @@ pub struct AudioStats {
     pub noise_floor: f32,
     pub is_clipping: bool,
     pub latency: LatencyBreakdown,
-    pub packet_loss: f32,
+    /// Paketverlust Client -> Server in Prozent (laut Server-Statistik)
+    pub uplink_loss: f32,
+    /// Paketverlust Server -> Client in Prozent (lokal gemessen)
+    pub downlink_loss: f32,
     pub rtt: f32,
     pub bitrate: f32,
+    /// Unterlaeufe des Playback-Puffers (Knackser) seit Start des Voice-Clients
+    pub playback_underruns: u64,
+    /// Geschaetzte Sprachqualitaet der eigenen Sitzung (MOS 1.0–4.5)
+    pub mos: Option<f32>,
+    /// Codec-Fehler seit Start des Voice-Clients
+    pub codec_errors: CodecErrorStats,
+}
+
+/// Codec-Fehler je Kategorie
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct CodecErrorStats {
+    /// Frames mit falscher Sample-Anzahl
+    pub invalid_frame_size: u64,
+    /// Beschaedigte oder leere Pakete
+    pub invalid_packet: u64,
+    /// Pakete mit mehr Samples als der Decoder-Puffer fasst
+    pub buffer_too_small: u64,
+    /// Sonstige Fehler aus libopus
+    pub internal_opus_error: u64,
+    /// Zuletzt von libopus gemeldeter Fehlercode
+    pub last_opus_code: Option<i32>,
+    /// Zu kurze Frames, die mit Stille aufgefuellt wurden
+    pub frames_padded: u64,
+    /// Decoder-Neustarts nach Fehlern in Folge
+    pub decoder_resets: u64,
+}
+
+impl From<CodecStatistik> for CodecErrorStats {
+    fn from(s: CodecStatistik) -> Self {
+        Self {
+            invalid_frame_size: s.ungueltige_frame_groesse,
+            invalid_packet: s.ungueltiges_paket,
+            buffer_too_small: s.puffer_zu_klein,
+            internal_opus_error: s.opus_intern,
+            last_opus_code: s.letzter_opus_code,
+            frames_padded: s.frames_aufgefuellt,
+            decoder_resets: s.decoder_resets,
+        }
+    }
+}
+
+/// Downlink-Verlust eines entfernten Sprechers
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RemoteStreamStats {
+    pub ssrc: u32,
+    pub user_id: Option<String>,
+    pub received: u64,
+    pub lost: u64,
+    /// Verlust im letzten Intervall in Prozent
+    pub loss: f32,
+    /// Wegen voller Mischer-Warteschlange verworfene Frames (kein Verlust)
+    pub overflow: u64,
+}
+
+/// Verbindungsdiagnose der laufenden Voice-Sitzung
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct VoiceDiagnostics {
+    pub uplink_loss: f32,
+    pub downlink_loss: f32,
+    pub remote: Vec<RemoteStreamStats>,
+    /// DSCP-Markierung des Voice-UDP-Sockets
+    pub voice_qos: QosStatus,
+    /// DSCP-Markierung des TCP-Control-Sockets
+    pub control_qos: QosStatus,
+    /// Kernel-Zaehler des Voice-UDP-Sockets (`None` = nicht verfuegbar)
+    pub voice_socket: Option<SocketZaehler>,
+    /// Codec-Fehler seit Start des Voice-Clients
+    pub codec_errors: CodecErrorStats,
+}
+
+/// Strukturierte Diagnose von Control-Verbindung und Voice-Transport
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ConnectionDiagnostics {
+    /// Laufzeit des letzten Pings der Control-Verbindung (`None` = noch keine Antwort)
+    pub tcp_rtt_ms: Option<f64>,
+    /// Median der UDP-Laufzeit zum Voice-Port (`None` = nicht messbar)
+    pub udp_rtt_ms: Option<f64>,
+    /// Gesendete Voice-Pakete seit Start des Voice-Clients
+    pub packets_sent: u64,
+    /// Empfangene Voice-Pakete seit Start des Voice-Clients
+    pub packets_received: u64,
+    /// Im Downlink verlorene Pakete der laufenden Sitzung
+    pub packets_lost: u64,
+    /// Aktuelle Tiefe des Jitter-Puffers
+    pub jitter_buffer_ms: u32,
+    /// Aktuelle Opus-Bitrate (`None` = keine Voice-Sitzung)
+    pub bitrate_kbps: Option<u16>,
+    pub local_udp_addr: Option<String>,
+    pub remote_udp_addr: Option<String>,
+    /// Krypto-Modus des Servers (`none`, `dtls`, `e2e`)
+    pub crypto_mode: Option<String>,
+}
+
+/// DSCP-Einstellungen (`None` = keine Markierung)
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct QosSettings {
+    /// Voice-Pakete (Standard: EF = 46)
+    pub voice_dscp: Option<u8>,
+    /// Control-Verbindung (z.B. AF31 = 26), wirkt ab der naechsten Verbindung
+    pub control_dscp: Option<u8>,
+}
+
+impl Default for QosSettings {
+    fn default() -> Self {
+        Self {
+            voice_dscp: Some(DSCP_EF),
+            control_dscp: None,
+        }
+    }
+}
+
+/// Erkennung einer Stummschaltung per Headset-Taste bzw. im Betriebssystem
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct HardwareMuteSettings {
+    pub enabled: bool,
+    /// Dauer eines exakt digitalen Null-Signals bis zur Meldung
+    pub zero_signal_ms: u32,
+    /// Eigene Stummschaltung dem erkannten Zustand folgen lassen
+    pub auto_sync: bool,
+}
+
+impl HardwareMuteSettings {
+    /// Null-Signal-Dauer fuer den Voice-Client (`None` = Erkennung aus)
+    pub fn null_dauer(&self) -> Option<std::time::Duration> {
+        self.enabled
+            .then(|| std::time::Duration::from_millis(self.zero_signal_ms as u64))
+    }
+}
+
+impl Default for HardwareMuteSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            zero_signal_ms: STANDARD_NULL_DAUER.as_millis() as u32,
+            auto_sync: false,
+        }
+    }
+}
+
+/// Exportierte Client-Einstellungen (Sicherung bzw. Umzug auf einen anderen Rechner)
+///
+/// Fehlende Teile bleiben beim Import unveraendert.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct SettingsExport {
+    #[serde(default)]
+    pub audio: Option<AudioSettingsConfig>,
+    #[serde(default)]
+    pub event_sounds: Option<EventSoundSettings>,
+    #[serde(default)]
+    pub qos: Option<QosSettings>,
+    #[serde(default)]
+    pub hardware_mute: Option<HardwareMuteSettings>,
+    /// Lautstaerke und Stummschaltung pro Benutzer (nur mit eigenem Flag)
+    #[serde(default)]
+    pub user_audio: Option<BenutzerAudioEinstellungen>,
+    /// Entwuerfe des Eingabefelds (wie `user_audio` nur mit eigenem Flag)
+    #[serde(default)]
+    pub drafts: Option<EntwurfsEinstellungen>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -643,7 +1381,7 @@ pub struct CalibrationResult {
     pub noise_floor: f32,
 }
 
-fn default_audio_settings() -> AudioSettingsConfig {
+pub fn default_audio_settings() -> AudioSettingsConfig {
     AudioSettingsConfig {
         input_device_id: None,
         output_device_id: None,
@@ -661,7 +1399,8 @@ fn default_audio_settings() -> AudioSettingsConfig {
             frame_size: 20,
             application: "voip".to_string(),
             fec: true,
-            dtx: false,
+            dtx: true,
+            dtx_keepalive_ms: standard_dtx_keepalive_ms(),
             channels: "mono".to_string(),
         },
         dsp: DspConfig {
@@ -685,6 +1424,7 @@ fn default_audio_settings() -> AudioSettingsConfig {
             echo_cancellation: EchoCancellationConfig {
                 enabled: false,
                 tail_length: 100,
+                delay_offset_ms: standard_echo_versatz_ms(),
             },
             deesser: DeesserConfig {
                 enabled: false,
@@ -697,10 +1437,33 @@ fn default_audio_settings() -> AudioSettingsConfig {
             min_buffer: 20,
             max_buffer: 200,
             adaptive: true,
+            latency_budget: STANDARD_BUDGET_MS,
         },
     }
 }
 
+/// Leitet die Engine-Konfiguration (Geraete, Capture, PTT) aus den Audio-Einstellungen ab
+pub fn engine_config_fuer(
+    basis: Option<AudioEngineConfig>,
+    config: &AudioSettingsConfig,
+    ptt_modus: PttMode,
+) -> AudioEngineConfig {
+    use speakeasy_audio::capture::CaptureConfig;
+
+    let mut engine_config = basis.unwrap_or_default();
+    engine_config.input_device = config.input_device_id.clone();
+    engine_config.output_device = config.output_device_id.clone();
+
+    let mut capture = CaptureConfig::default();
+    capture.sample_rate = config.codec.sample_rate;
+    capture.buffer_size = config.codec.buffer_size as usize;
+    engine_config.capture = capture;
+
+    // PTT-Modus aus Voice-Mode ableiten
+    engine_config.ptt_mode = ptt_modus;
+    engine_config
+}
+
 // --- Audio-Commands (Phase 3) ---
 
 /// Gibt die aktuellen Audio-Einstellungen zurueck
@@ -720,9 +1483,12 @@ pub async fn get_audio_settings(state: State<'_, AppState>) -> Result<AudioSetti
 /// Speichert Audio-Einstellungen (vollstaendig inkl. DSP, Codec, Jitter)
 #[tauri::command]
 pub async fn set_audio_settings(
+    app: tauri::AppHandle,
     state: State<'_, AppState>,
     config: AudioSettingsConfig,
 ) -> Result<(), String> {
+    validation::audio_einstellungen(&config)?;
+    let ptt_modus = validation::ptt(&config.voice_mode, config.ptt_key.as_deref())?;
     debug!(
         "Setze Audio-Einstellungen: input={:?}, output={:?}, dsp_noise_gate={}, dsp_suppression={}, dsp_agc={}",
         config.input_device_id,
@@ -732,34 +1498,67 @@ pub async fn set_audio_settings(
         config.dsp.agc.enabled,
     );
 
-    use speakeasy_audio::capture::CaptureConfig;
-
     let mut audio = state.audio.lock().map_err(|e| e.to_string())?;
+    audio.engine_config = Some(engine_config_fuer(
+        audio.engine_config.clone(),
+        &config,
+        ptt_modus,
+    ));
+
+    // Vollstaendige Settings merken (inkl. DSP, Codec, Jitter)
+    let ptt_taste = config.ptt_key.clone();
+    audio.full_settings = Some(config.clone());
+    drop(audio);
+
+    if let Err(e) = state.einstellungen.audio_speichern(config) {
+        warn!("Audio-Einstellungen nicht gespeichert: {}", e);
+    }
+    info!("Audio-Einstellungen gespeichert (inkl. DSP-Pipeline-Konfiguration)");
 
-    // Engine-Config aktualisieren (Device + Capture)
-    let mut engine_config = audio.engine_config.clone().unwrap_or_default();
-    engine_config.input_device = config.input_device_id.clone();
-    engine_config.output_device = config.output_device_id.clone();
-
-    let mut capture = CaptureConfig::default();
-    capture.sample_rate = config.codec.sample_rate;
-    capture.buffer_size = config.codec.buffer_size as usize;
-    engine_config.capture = capture;
-
-    // PTT-Modus aus Voice-Mode ableiten
-    engine_config.ptt_mode = match config.voice_mode.as_str() {
-        "ptt_hold" => speakeasy_audio::ptt::PttMode::Hold,
-        "ptt_toggle" => speakeasy_audio::ptt::PttMode::Toggle,
-        _ => speakeasy_audio::ptt::PttMode::VoiceActivation,
-    };
+    // PTT-Taste systemweit (neu) registrieren
+    ptt::anwenden(&app, &state.ptt, ptt_modus, ptt_taste)
+}
 
-    audio.engine_config = Some(engine_config);
+/// Setzt Sprach-Modus und PTT-Taste und registriert die Taste systemweit
+///
+/// `mode` ist `vad`, `ptt_hold` oder `ptt_toggle`; die PTT-Modi brauchen
+/// eine Taste. Gespeicherte Audio-Einstellungen werden mitgezogen.
+#[tauri::command]
+pub async fn set_ptt_config(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    mode: String,
+    key: Option<String>,
+) -> Result<PttZustand, String> {
+    let modus = validation::ptt(&mode, key.as_deref())?;
+    ptt::anwenden(&app, &state.ptt, modus, key.clone())?;
 
-    // Vollstaendige Settings persistieren (inkl. DSP, Codec, Jitter)
-    audio.full_settings = Some(config);
+    {
+        let mut audio = state.audio.lock().map_err(|e| e.to_string())?;
+        if let Some(config) = audio.engine_config.as_mut() {
+            config.ptt_mode = modus;
+        }
+        if let Some(settings) = audio.full_settings.as_mut() {
+            settings.voice_mode = mode;
+            settings.ptt_key = key;
+            if let Err(e) = state.einstellungen.audio_speichern(settings.clone()) {
+                warn!("Audio-Einstellungen nicht gespeichert: {}", e);
+            }
+        }
+    }
+    get_ptt_state(state).await
+}
 
-    info!("Audio-Einstellungen gespeichert (inkl. DSP-Pipeline-Konfiguration)");
-    Ok(())
+/// Gibt Sprach-Modus, PTT-Taste und den aktuellen Sende-Zustand zurueck
+#[tauri::command]
+pub async fn get_ptt_state(state: State<'_, AppState>) -> Result<PttZustand, String> {
+    let speaking = state
+        .voice
+        .lock()
+        .await
+        .as_ref()
+        .is_some_and(|v| v.is_speaking());
+    Ok(state.ptt.zustand(speaking))
 }
 
 /// Startet die Auto-Kalibrierung (Noise-Floor-Messung)
@@ -882,7 +1681,7 @@ fn build_pipeline_from_dsp_config(dsp: &DspConfig) -> speakeasy_audio::pipeline:
         AudioProcessor,
         agc::{Agc, AgcConfig as RustAgcConfig},
         deesser::{DeEsser, DeEsserConfig as RustDeEsserConfig},
-        echo_cancel::{EchoCancelConfig as RustEchoCancelConfig, EchoCanceller},
+        echo_cancel::EchoCanceller,
         noise_gate::{NoiseGate, NoiseGateConfig as RustNoiseGateConfig},
         noise_suppression::{NoiseSuppressor, SuppressionLevel},
     };
@@ -920,10 +1719,7 @@ fn build_pipeline_from_dsp_config(dsp: &DspConfig) -> speakeasy_audio::pipeline:
     processors.push(Box::new(agc));
 
     // Echo Cancellation
-    let mut ec = EchoCanceller::new(RustEchoCancelConfig {
-        max_delay_samples: (dsp.echo_cancellation.tail_length as f32 * 48.0) as usize,
-        ..RustEchoCancelConfig::default()
-    });
+    let mut ec = EchoCanceller::new(dsp.echo_cancellation.aec_config());
     ec.set_enabled(dsp.echo_cancellation.enabled);
     processors.push(Box::new(ec));
 
@@ -1000,7 +1796,7 @@ pub async fn start_audio_monitor(state: State<'_, AppState>) -> Result<(), Strin
             // DSP-Pipeline aus aktuellen Settings bauen
             let mut pipeline = dsp_config
                 .map(|dsp| build_pipeline_from_dsp_config(&dsp))
-                .unwrap_or_else(speakeasy_audio::pipeline::build_default_capture_pipeline);
+                .unwrap_or_else(|| speakeasy_audio::pipeline::build_default_capture_pipeline(None));
 
             let stream = match device.build_input_stream(
                 &config,
@@ -1090,12 +1886,89 @@ pub async fn stop_audio_monitor(state: State<'_, AppState>) -> Result<(), String
     Ok(())
 }
 
-/// Gibt aktuelle Audio-Statistiken zurueck (mit echten Pegeln wenn Monitor laeuft)
-#[tauri::command]
-pub async fn get_audio_stats(state: State<'_, AppState>) -> Result<AudioStats, String> {
-    let audio = state.audio.lock().map_err(|e| e.to_string())?;
-
-    if let Some(ref monitor) = audio.monitor {
+/// Tauscht bei Bedarf die Voice-Statistik mit dem Server aus
+///
+/// Gibt die Statistik der laufenden Voice-Sitzung zurueck (None ohne Sitzung).
+/// Der Bericht wird hoechstens alle `BERICHT_INTERVALL` gesendet, auch wenn
+/// das Frontend haeufiger abfragt.
+async fn verbindungsstatistik_aktualisieren(
+    state: &AppState,
+) -> Option<std::sync::Arc<std::sync::Mutex<VerbindungsStatistik>>> {
+    let (statistik, hoechste_gesendet, bitrate_kbps) = {
+        let voice = state.voice.lock().await;
+        let client = voice.as_ref().filter(|v| v.is_running())?;
+        (
+            client.statistik(),
+            client.hoechste_gesendete_sequenz(),
+            client.bitrate_kbps(),
+        )
+    };
+
+    let jetzt = std::time::Instant::now();
+    let bericht = {
+        let mut stat = statistik.lock().ok()?;
+        stat.bitrate_setzen(bitrate_kbps);
+        stat.bericht_faellig(jetzt)
+            .then(|| stat.bericht_erstellen(hoechste_gesendet, jetzt))
+    };
+
+    if let Some(bericht) = bericht {
+        let mut tcp = state.tcp.lock().await;
+        if let Some(conn) = tcp.as_mut() {
+            let gesendet = std::time::Instant::now();
+            match conn.voice_stats(bericht).await {
+                Ok(antwort) => {
+                    if let Ok(mut stat) = statistik.lock() {
+                        stat.antwort_verarbeiten(&antwort);
+                        stat.rtt_setzen(gesendet.elapsed());
+                    }
+                }
+                Err(e) => debug!("Voice-Statistik konnte nicht ausgetauscht werden: {}", e),
+            }
+        }
+    }
+
+    Some(statistik)
+}
+
+/// Paketverlust (Uplink, Downlink) in Prozent und geschaetzte Sprachqualitaet
+async fn verbindungsqualitaet(state: &AppState) -> (f32, f32, Option<f32>) {
+    verbindungsstatistik_aktualisieren(state)
+        .await
+        .and_then(|stat| {
+            stat.lock().ok().map(|s| {
+                (
+                    s.uplink_verlust_prozent(),
+                    s.downlink_verlust_prozent(),
+                    s.mos().map(|mos| mos as f32),
+                )
+            })
+        })
+        .unwrap_or((0.0, 0.0, None))
+}
+
+/// Gibt aktuelle Audio-Statistiken zurueck (mit echten Pegeln wenn Monitor laeuft)
+#[tauri::command]
+pub async fn get_audio_stats(state: State<'_, AppState>) -> Result<AudioStats, String> {
+    let (uplink_loss, downlink_loss, mos) = verbindungsqualitaet(&state).await;
+    let (playback_underruns, codec_errors) = state
+        .voice
+        .lock()
+        .await
+        .as_ref()
+        .map(|v| (v.playback_unterlaeufe(), v.codec_statistik().into()))
+        .unwrap_or_default();
+    let voice = voice_messwerte(&state).await;
+    // Bevorzugt der Ping der Control-Verbindung, sonst der Statistik-Austausch
+    let rtt_ms = tcp_rtt_ms(&state)
+        .await
+        .or_else(|| voice.as_ref()?.statistik_rtt.map(dauer_ms));
+    let latency = latenz_aufschluesseln(voice.as_ref(), rtt_ms);
+    let rtt = rtt_ms.unwrap_or(0.0) as f32;
+    let bitrate = voice.as_ref().map_or(0.0, |v| f32::from(v.bitrate_kbps));
+    let audio = state.audio.lock().map_err(|e| e.to_string())?;
+
+    if let Some(ref monitor) = audio.monitor {
         let lvl = monitor.levels.lock().map_err(|e| e.to_string())?;
         Ok(AudioStats {
             input_level: lvl.input_level,
@@ -1103,56 +1976,433 @@ pub async fn get_audio_stats(state: State<'_, AppState>) -> Result<AudioStats, S
             processed_level: lvl.processed_level,
             noise_floor: lvl.noise_floor,
             is_clipping: lvl.is_clipping,
-            latency: LatencyBreakdown {
-                device: 10.0,
-                encoding: 20.0,
-                jitter: 40.0,
-                network: 0.0,
-                total: 70.0,
-            },
-            packet_loss: 0.0,
-            rtt: 0.0,
-            bitrate: 0.0,
+            latency,
+            uplink_loss,
+            downlink_loss,
+            rtt,
+            bitrate,
+            playback_underruns,
+            mos,
+            codec_errors,
         })
     } else {
-        // Kein Monitor aktiv -> Nullwerte
+        // Kein Monitor aktiv -> keine Pegel
         Ok(AudioStats {
             input_level: 0.0,
             output_level: 0.0,
             processed_level: 0.0,
             noise_floor: -60.0,
             is_clipping: false,
-            latency: LatencyBreakdown {
-                device: 0.0,
-                encoding: 0.0,
-                jitter: 0.0,
-                network: 0.0,
-                total: 0.0,
-            },
-            packet_loss: 0.0,
-            rtt: 0.0,
-            bitrate: 0.0,
+            latency,
+            uplink_loss,
+            downlink_loss,
+            rtt,
+            bitrate,
+            playback_underruns,
+            mos,
+            codec_errors,
         })
     }
 }
 
+/// Geschaetzte Latenz der Audio-Geraete (nicht messbar)
+const GERAETE_LATENZ_MS: f32 = 10.0;
+/// Proben der UDP-Laufzeitmessung fuer die Verbindungsdiagnose
+const DIAGNOSE_PROBEN: usize = 3;
+/// Wartezeit auf die letzte Probe der Verbindungsdiagnose
+const DIAGNOSE_ZEITLIMIT: std::time::Duration = std::time::Duration::from_millis(500);
+
+/// Messwerte der laufenden Voice-Pipeline
+struct VoiceMesswerte {
+    gesendet: u64,
+    empfangen: u64,
+    verloren: u64,
+    puffer_ms: u32,
+    bitrate_kbps: u16,
+    frame_ms: f32,
+    /// Dauer des letzten Statistik-Austauschs mit dem Server
+    statistik_rtt: Option<std::time::Duration>,
+    lokal: Option<std::net::SocketAddr>,
+    server: std::net::SocketAddr,
+    krypto_modus: Option<String>,
+}
+
+/// Liest Zaehler und Statistik der Voice-Pipeline (`None` = Pipeline steht)
+async fn voice_messwerte(state: &AppState) -> Option<VoiceMesswerte> {
+    let voice = state.voice.lock().await;
+    let client = voice.as_ref().filter(|v| v.is_running())?;
+    let statistik = client.statistik();
+    let (verloren, puffer_ms, statistik_rtt) = statistik
+        .lock()
+        .ok()
+        .map(|s| (s.downlink_verloren(), s.puffer_stand().tiefe_ms, s.rtt()))
+        .unwrap_or_default();
+    let pakete = client.paket_zaehler();
+    Some(VoiceMesswerte {
+        gesendet: pakete.gesendet(),
+        empfangen: pakete.empfangen(),
+        verloren,
+        puffer_ms,
+        bitrate_kbps: client.bitrate_kbps(),
+        frame_ms: client.frame_ms(),
+        statistik_rtt,
+        lokal: client.lokale_adresse(),
+        server: client.server_adresse(),
+        krypto_modus: client.sitzung().map(|s| s.crypto_mode.clone()),
+    })
+}
+
+/// Laufzeit des letzten Pings der Control-Verbindung in ms
+async fn tcp_rtt_ms(state: &AppState) -> Option<f64> {
+    state.tcp.lock().await.as_ref()?.letzte_rtt().map(dauer_ms)
+}
+
+/// Dauer in Millisekunden
+fn dauer_ms(dauer: std::time::Duration) -> f64 {
+    dauer.as_secs_f64() * 1000.0
+}
+
+/// Latenz je Abschnitt: Frame-Dauer, Tiefe des Jitter-Puffers und halbe RTT;
+/// ohne Voice-Sitzung alles 0
+fn latenz_aufschluesseln(voice: Option<&VoiceMesswerte>, rtt_ms: Option<f64>) -> LatencyBreakdown {
+    let Some(voice) = voice else {
+        return LatencyBreakdown {
+            device: 0.0,
+            encoding: 0.0,
+            jitter: 0.0,
+            network: 0.0,
+            total: 0.0,
+        };
+    };
+    let encoding = voice.frame_ms;
+    let jitter = voice.puffer_ms as f32;
+    let network = rtt_ms.map_or(0.0, |rtt| (rtt / 2.0) as f32);
+    LatencyBreakdown {
+        device: GERAETE_LATENZ_MS,
+        encoding,
+        jitter,
+        network,
+        total: GERAETE_LATENZ_MS + encoding + jitter + network,
+    }
+}
+
+/// Strukturierte Verbindungsdiagnose fuer die Fehlersuche
+///
+/// Laeuft Voice, wird zusaetzlich die UDP-Laufzeit zum Voice-Port des
+/// Servers gemessen (eigener Socket, dauert knapp eine Sekunde).
+#[tauri::command]
+pub async fn get_connection_diagnostics(
+    state: State<'_, AppState>,
+) -> Result<ConnectionDiagnostics, String> {
+    let tcp_rtt_ms = tcp_rtt_ms(&state).await;
+    let Some(voice) = voice_messwerte(&state).await else {
+        return Ok(ConnectionDiagnostics {
+            tcp_rtt_ms,
+            udp_rtt_ms: None,
+            packets_sent: 0,
+            packets_received: 0,
+            packets_lost: 0,
+            jitter_buffer_ms: 0,
+            bitrate_kbps: None,
+            local_udp_addr: None,
+            remote_udp_addr: None,
+            crypto_mode: None,
+        });
+    };
+
+    let udp_rtt_ms = match server_ping::messen(
+        &voice.server.ip().to_string(),
+        voice.server.port(),
+        DIAGNOSE_PROBEN,
+        server_ping::ABSTAND,
+        DIAGNOSE_ZEITLIMIT,
+    )
+    .await
+    {
+        Ok(messung) => messung.median_ms,
+        Err(e) => {
+            debug!("UDP-Laufzeit nicht messbar: {}", e);
+            None
+        }
+    };
+
+    Ok(ConnectionDiagnostics {
+        tcp_rtt_ms,
+        udp_rtt_ms,
+        packets_sent: voice.gesendet,
+        packets_received: voice.empfangen,
+        packets_lost: voice.verloren,
+        jitter_buffer_ms: voice.puffer_ms,
+        bitrate_kbps: Some(voice.bitrate_kbps),
+        local_udp_addr: voice.lokal.map(|a| a.to_string()),
+        remote_udp_addr: Some(voice.server.to_string()),
+        crypto_mode: voice.krypto_modus,
+    })
+}
+
+/// Holt die Ereignisse der Voice-Pipeline ab (z.B. uebersprungene Streams)
+///
+/// Eine dabei gemeldete Hardware-Stummschaltung wird sofort uebernommen.
+#[tauri::command]
+pub async fn take_voice_events(state: State<'_, AppState>) -> Result<Vec<VoiceEreignis>, String> {
+    let ereignisse = {
+        let voice = state.voice.lock().await;
+        voice
+            .as_ref()
+            .map(|v| v.ereignisse_abholen())
+            .unwrap_or_default()
+    };
+
+    let hardware_stumm = ereignisse.iter().rev().find_map(|e| match e {
+        VoiceEreignis::HardwareMuteDetected { muted } => Some(*muted),
+        _ => None,
+    });
+    if let Some(hardware_stumm) = hardware_stumm {
+        hardware_stumm_uebernehmen(&state, hardware_stumm).await?;
+    }
+    Ok(ereignisse)
+}
+
+/// Uebernimmt eine erkannte Hardware-Stummschaltung
+///
+/// Mit `auto_sync` folgt die eigene Stummschaltung (ausser bei deaf). Der
+/// Server erfaehrt in jedem Fall den wirksamen Zustand, damit andere das
+/// richtige Symbol sehen.
+async fn hardware_stumm_uebernehmen(
+    state: &State<'_, AppState>,
+    hardware_stumm: bool,
+) -> Result<(), String> {
+    let auto_sync = state
+        .hardware_stumm
+        .lock()
+        .map_err(|e| e.to_string())?
+        .auto_sync;
+    let (muted, eingabe_stumm) = {
+        let mut audio = state.audio.lock().map_err(|e| e.to_string())?;
+        audio.hardware_muted = hardware_stumm;
+        if auto_sync && !audio.deafened {
+            audio.muted = hardware_stumm;
+        }
+        (audio.muted, audio.muted || hardware_stumm)
+    };
+    info!(
+        "Hardware-Stummschaltung: {} (Auto-Sync: {})",
+        if hardware_stumm { "an" } else { "aus" },
+        auto_sync
+    );
+
+    if auto_sync {
+        let voice = state.voice.lock().await;
+        if let Some(ref client) = *voice {
+            client.set_muted(muted);
+        }
+    }
+
+    let mut tcp = state.tcp.lock().await;
+    if let Some(conn) = tcp.as_mut() {
+        if let Err(e) = conn.set_input_muted(eingabe_stumm).await {
+            warn!("Mikrofon-Zustand konnte nicht gemeldet werden: {}", e);
+        }
+    }
+    Ok(())
+}
+
+/// Uebernimmt den Vorschlag einer Hintergrundgeraeusch-Warnung
+///
+/// Schaltet die Rauschunterdrueckung ein bzw. eine Stufe hoeher (low ->
+/// medium -> high) und gibt die neue Stufe zurueck. Wirkt wie jede andere
+/// DSP-Einstellung ab dem naechsten Pipeline-Aufbau.
+#[tauri::command]
+pub async fn apply_noise_suggestion(state: State<'_, AppState>) -> Result<String, String> {
+    let mut audio = state.audio.lock().map_err(|e| e.to_string())?;
+    let stufe = rauschunterdrueckung_verstaerken(
+        audio
+            .full_settings
+            .get_or_insert_with(default_audio_settings),
+    );
+    info!("Rauschunterdrueckung nach Warnung verstaerkt: {}", stufe);
+    Ok(stufe)
+}
+
+/// Schaltet die Rauschunterdrueckung ein bzw. eine Stufe hoeher und gibt
+/// die neue Stufe zurueck
+pub fn rauschunterdrueckung_verstaerken(settings: &mut AudioSettingsConfig) -> String {
+    let ns = &mut settings.dsp.noise_suppression;
+    if ns.enabled {
+        ns.level = match ns.level.as_str() {
+            "low" => "medium",
+            _ => "high",
+        }
+        .to_string();
+    } else {
+        ns.enabled = true;
+    }
+    settings.noise_suppression = ns.level.clone();
+    ns.level.clone()
+}
+
+/// Stellt den Jitter-Puffer auf einen Modus um (`low_latency` oder `stability`)
+///
+/// Die Oberflaeche bietet `stability` nach einem `network_unstable`-Ereignis
+/// an. Gibt die neue Konfiguration zurueck; wirkt ab dem naechsten
+/// Pipeline-Aufbau und wird gespeichert.
+#[tauri::command]
+pub async fn apply_jitter_mode(
+    state: State<'_, AppState>,
+    mode: String,
+) -> Result<JitterConfig, String> {
+    let jitter =
+        JitterConfig::modus(&mode).ok_or_else(|| format!("Unbekannter Jitter-Modus: {}", mode))?;
+    let mut audio = state.audio.lock().map_err(|e| e.to_string())?;
+    let settings = audio
+        .full_settings
+        .get_or_insert_with(default_audio_settings);
+    settings.jitter = jitter.clone();
+    let settings = settings.clone();
+    drop(audio);
+
+    if let Err(e) = state.einstellungen.audio_speichern(settings) {
+        warn!("Audio-Einstellungen nicht gespeichert: {}", e);
+    }
+    info!(
+        "Jitter-Modus {}: Budget {} ms, Puffer {}-{} ms",
+        mode, jitter.latency_budget, jitter.min_buffer, jitter.max_buffer
+    );
+    Ok(jitter)
+}
+
+/// Gibt die Verbindungsdiagnose zurueck (Verlust je Richtung und je Sprecher)
+#[tauri::command]
+pub async fn get_voice_diagnostics(state: State<'_, AppState>) -> Result<VoiceDiagnostics, String> {
+    let voice_qos = state
+        .voice
+        .lock()
+        .await
+        .as_ref()
+        .filter(|v| v.is_running())
+        .map(|v| v.qos_status().clone())
+        .unwrap_or(QosStatus::Deaktiviert);
+    let control_qos = state
+        .tcp
+        .lock()
+        .await
+        .as_ref()
+        .map(|c| c.qos_status().clone())
+        .unwrap_or(QosStatus::Deaktiviert);
+    let codec_errors = state
+        .voice
+        .lock()
+        .await
+        .as_ref()
+        .map(|v| v.codec_statistik().into())
+        .unwrap_or_default();
+
+    let Some(statistik) = verbindungsstatistik_aktualisieren(&state).await else {
+        return Ok(VoiceDiagnostics {
+            uplink_loss: 0.0,
+            downlink_loss: 0.0,
+            remote: vec![],
+            voice_qos,
+            control_qos,
+            voice_socket: None,
+            codec_errors,
+        });
+    };
+    // Sprecher bevorzugt ueber die vom Server gepflegte SSRC-Zuordnung aufloesen
+    let zuordnung = state
+        .tcp
+        .lock()
+        .await
+        .as_ref()
+        .map(|c| c.ssrc_zuordnung().clone())
+        .unwrap_or_default();
+    let stat = statistik.lock().map_err(|e| e.to_string())?;
+    Ok(VoiceDiagnostics {
+        uplink_loss: stat.uplink_verlust_prozent(),
+        downlink_loss: stat.downlink_verlust_prozent(),
+        remote: stat
+            .entfernte()
+            .into_iter()
+            .map(|e| RemoteStreamStats {
+                ssrc: e.ssrc,
+                user_id: zuordnung
+                    .user_von_ssrc(e.ssrc)
+                    .map(|u| u.inner().to_string())
+                    .or(e.user_id),
+                received: e.empfangen,
+                lost: e.verloren,
+                loss: (e.verlust_rate * 100.0) as f32,
+                overflow: e.ueberlauf,
+            })
+            .collect(),
+        voice_qos,
+        control_qos,
+        voice_socket: stat.socket_zaehler(),
+        codec_errors,
+    })
+}
+
+/// Momentaufnahme der Voice-Pipeline fuer die Fehlersuche (geschwaerzt)
+///
+/// Geraete, DSP-Stufen, Encoder, Jitter-Puffer, Transport, Krypto-Modus und
+/// Zaehler – siehe [`voice_debug`].
+#[tauri::command]
+pub async fn get_voice_debug_state(
+    state: State<'_, AppState>,
+) -> Result<serde_json::Value, String> {
+    voice_debug::geschwaerzt(&voice_debug_zustand(&state).await?)
+}
+
+async fn voice_debug_zustand(state: &AppState) -> Result<VoiceDebugZustand, String> {
+    let pipeline = state.voice.lock().await.as_ref().map(|v| v.debug_zustand());
+    let audio = state.audio.lock().map_err(|e| e.to_string())?;
+    Ok(VoiceDebugZustand::zusammenstellen(&audio, pipeline))
+}
+
+/// Support-Paket: alles, was der Support fuer die Fehlersuche braucht
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SupportBundle {
+    pub created_at: String,
+    pub client_version: String,
+    pub server_address: Option<String>,
+    pub current_channel: Option<String>,
+    /// Einstellungen ohne Lautstaerken pro Benutzer
+    pub settings: SettingsExport,
+    pub voice_debug: VoiceDebugZustand,
+    pub voice_diagnostics: VoiceDiagnostics,
+}
+
+/// Stellt das Support-Paket zusammen (geschwaerzt: keine Tokens, keine Passwoerter)
+#[tauri::command]
+pub async fn export_support_bundle(
+    state: State<'_, AppState>,
+) -> Result<serde_json::Value, String> {
+    let (server_address, current_channel) = {
+        let conn = state.connection.lock().map_err(|e| e.to_string())?;
+        (conn.server_address.clone(), conn.current_channel.clone())
+    };
+    let paket = SupportBundle {
+        created_at: chrono_now(),
+        client_version: env!("CARGO_PKG_VERSION").to_string(),
+        server_address,
+        current_channel,
+        settings: export_settings(state.clone(), Some(false)).await?,
+        voice_debug: voice_debug_zustand(&state).await?,
+        voice_diagnostics: get_voice_diagnostics(state.clone()).await?,
+    };
+    info!("Support-Paket erstellt");
+    voice_debug::geschwaerzt(&paket)
+}
+
 /// Spielt einen Testton (440 Hz Sinus) ab
+///
+/// Der Ton wird im nativen Format und mit der nativen Rate des
+/// Ausgabegeraets erzeugt, damit nichts doppelt umgerechnet wird.
 #[tauri::command]
 pub async fn play_test_sound() -> Result<(), String> {
     debug!("Spiele Testton ab");
 
-    // 440 Hz Sinus bei 48kHz fuer 0.5 Sekunden generieren
-    let sample_rate = 48000u32;
-    let duration_samples = sample_rate / 2; // 0.5 Sekunden
-    let frequency = 440.0f32;
-
-    let samples: Vec<f32> = (0..duration_samples)
-        .map(|i| {
-            let t = i as f32 / sample_rate as f32;
-            0.3 * (2.0 * std::f32::consts::PI * frequency * t).sin()
-        })
-        .collect();
-
     // Playback ueber cpal in eigenem Thread (nicht blockierend fuer Tauri)
     let result = std::thread::spawn(move || -> Result<(), String> {
         use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
@@ -1166,35 +2416,31 @@ pub async fn play_test_sound() -> Result<(), String> {
             .default_output_config()
             .map_err(|e| e.to_string())?;
 
-        let channels = config.channels() as usize;
-        let mut sample_idx = 0usize;
-        let samples_len = samples.len();
+        // 440 Hz Sinus fuer 0.5 Sekunden bei der Geraete-Rate
+        let sample_rate = config.sample_rate().0;
+        let duration_samples = sample_rate / 2;
+        let frequency = 440.0f32;
+        let samples: Vec<f32> = (0..duration_samples)
+            .map(|i| {
+                let t = i as f32 / sample_rate as f32;
+                0.3 * (2.0 * std::f32::consts::PI * frequency * t).sin()
+            })
+            .collect();
+
         let (done_tx, done_rx) = std::sync::mpsc::channel::<()>();
 
         let stream = match config.sample_format() {
-            cpal::SampleFormat::F32 => device.build_output_stream(
-                &config.into(),
-                move |data: &mut [f32], _| {
-                    for frame in data.chunks_mut(channels) {
-                        let val = if sample_idx < samples_len {
-                            samples[sample_idx]
-                        } else {
-                            0.0
-                        };
-                        sample_idx += 1;
-                        for sample in frame.iter_mut() {
-                            *sample = val;
-                        }
-                        if sample_idx >= samples_len {
-                            let _ = done_tx.send(());
-                        }
-                    }
-                },
-                |e| tracing::error!("Testton-Fehler: {}", e),
-                None,
-            ),
-            _ => {
-                return Err("Nicht unterstuetztes Audio-Format".to_string());
+            cpal::SampleFormat::F32 => {
+                testton_stream::<f32>(&device, &config.config(), samples, done_tx)
+            }
+            cpal::SampleFormat::I16 => {
+                testton_stream::<i16>(&device, &config.config(), samples, done_tx)
+            }
+            cpal::SampleFormat::U16 => {
+                testton_stream::<u16>(&device, &config.config(), samples, done_tx)
+            }
+            format => {
+                return Err(format!("Nicht unterstuetztes Audio-Format: {:?}", format));
             }
         }
         .map_err(|e| e.to_string())?;
@@ -1221,6 +2467,443 @@ pub async fn play_test_sound() -> Result<(), String> {
     }
 }
 
+/// Baut den Output-Stream des Testtons fuer Geraete-Samples vom Typ `T`
+fn testton_stream<T>(
+    device: &cpal::Device,
+    config: &cpal::StreamConfig,
+    samples: Vec<f32>,
+    done_tx: std::sync::mpsc::Sender<()>,
+) -> Result<cpal::Stream, cpal::BuildStreamError>
+where
+    T: speakeasy_audio::GeraeteSample + cpal::SizedSample,
+{
+    use cpal::traits::DeviceTrait;
+
+    let channels = config.channels as usize;
+    let mut sample_idx = 0usize;
+    device.build_output_stream(
+        config,
+        move |data: &mut [T], _| {
+            for frame in data.chunks_mut(channels) {
+                let val = T::aus_f32(samples.get(sample_idx).copied().unwrap_or(0.0));
+                sample_idx += 1;
+                for sample in frame.iter_mut() {
+                    *sample = val;
+                }
+                if sample_idx >= samples.len() {
+                    let _ = done_tx.send(());
+                }
+            }
+        },
+        |e| tracing::error!("Testton-Fehler: {}", e),
+        None,
+    )
+}
+
+// --- Event-Sounds ---
+
+/// Spielt einen Event-Sound ueber die Effekt-Quelle der Voice-Pipeline ab
+///
+/// Beruecksichtigt Event-Flags, Deaf-Status und die Karenzzeit nach dem
+/// Kanal-Beitritt. Ohne laufende Voice-Pipeline wird nichts abgespielt.
+pub async fn event_sound_abspielen(state: &AppState, ereignis: SoundEreignis) {
+    let samples = {
+        let deafened = match state.audio.lock() {
+            Ok(audio) => audio.deafened,
+            Err(_) => return,
+        };
+        let Ok(sounds) = state.event_sounds.lock() else {
+            return;
+        };
+        sounds.samples_fuer(ereignis, deafened, std::time::Instant::now())
+    };
+    let Some(samples) = samples else {
+        return;
+    };
+
+    let voice = state.voice.lock().await;
+    match voice.as_ref() {
+        Some(client) if client.effekt_abspielen(&samples) => {
+            debug!("Event-Sound abgespielt: {:?}", ereignis);
+        }
+        _ => debug!(
+            "Event-Sound {:?} verworfen: keine aktive Voice-Pipeline",
+            ereignis
+        ),
+    }
+}
+
+/// Gibt die aktuellen Event-Sound-Einstellungen zurueck
+#[tauri::command]
+pub async fn get_event_sound_settings(
+    state: State<'_, AppState>,
+) -> Result<EventSoundSettings, String> {
+    let sounds = state.event_sounds.lock().map_err(|e| e.to_string())?;
+    Ok(sounds.einstellungen().clone())
+}
+
+/// Speichert Event-Sound-Einstellungen
+///
+/// Ein eigenes Sound-Pack wird dabei geladen und validiert (Format, Groesse,
+/// Dauer). Bei ungueltigem Pack bleiben die bisherigen Einstellungen aktiv.
+#[tauri::command]
+pub async fn set_event_sound_settings(
+    state: State<'_, AppState>,
+    config: EventSoundSettings,
+) -> Result<(), String> {
+    let config = validation::event_sounds(config)?;
+    debug!(
+        "Setze Event-Sound-Einstellungen: volume={}, ducking={}, pack={:?}",
+        config.volume, config.max_ducking, config.sound_pack_dir
+    );
+
+    let ducking = config.ducking_faktor();
+    {
+        let mut sounds = state.event_sounds.lock().map_err(|e| e.to_string())?;
+        sounds.einstellungen_setzen(config)?;
+    }
+
+    let voice = state.voice.lock().await;
+    if let Some(ref client) = *voice {
+        client.set_event_ducking(ducking);
+    }
+
+    info!("Event-Sound-Einstellungen gespeichert");
+    Ok(())
+}
+
+/// Gibt die aktuellen DSCP-Einstellungen zurueck
+#[tauri::command]
+pub async fn get_qos_settings(state: State<'_, AppState>) -> Result<QosSettings, String> {
+    let qos = state.qos.lock().map_err(|e| e.to_string())?;
+    Ok(qos.clone())
+}
+
+/// Speichert DSCP-Einstellungen
+///
+/// Die Voice-Markierung gilt ab dem naechsten Kanalbeitritt, die
+/// Control-Markierung ab der naechsten Verbindung.
+#[tauri::command]
+pub async fn set_qos_settings(
+    state: State<'_, AppState>,
+    config: QosSettings,
+) -> Result<(), String> {
+    validation::qos(&config)?;
+    debug!(
+        "Setze DSCP-Einstellungen: voice={:?}, control={:?}",
+        config.voice_dscp, config.control_dscp
+    );
+
+    *state.qos.lock().map_err(|e| e.to_string())? = config;
+    info!("DSCP-Einstellungen gespeichert");
+    Ok(())
+}
+
+/// Gibt die Einstellungen der Hardware-Stumm-Erkennung zurueck
+#[tauri::command]
+pub async fn get_hardware_mute_settings(
+    state: State<'_, AppState>,
+) -> Result<HardwareMuteSettings, String> {
+    let einstellungen = state.hardware_stumm.lock().map_err(|e| e.to_string())?;
+    Ok(einstellungen.clone())
+}
+
+/// Speichert die Einstellungen der Hardware-Stumm-Erkennung
+///
+/// Erkennung und Null-Signal-Dauer gelten ab dem naechsten Kanalbeitritt,
+/// `auto_sync` sofort.
+#[tauri::command]
+pub async fn set_hardware_mute_settings(
+    state: State<'_, AppState>,
+    config: HardwareMuteSettings,
+) -> Result<(), String> {
+    validation::hardware_stumm(&config)?;
+    debug!(
+        "Setze Hardware-Stumm-Erkennung: aktiv={}, null_signal={}ms, auto_sync={}",
+        config.enabled, config.zero_signal_ms, config.auto_sync
+    );
+
+    *state.hardware_stumm.lock().map_err(|e| e.to_string())? = config;
+    info!("Hardware-Stumm-Einstellungen gespeichert");
+    Ok(())
+}
+
+/// Gibt die gespeicherten Lautstaerken pro Benutzer zurueck
+#[tauri::command]
+pub async fn get_user_audio_preferences(
+    state: State<'_, AppState>,
+) -> Result<BenutzerAudioEinstellungen, String> {
+    Ok(state.benutzer_pegel.einstellungen())
+}
+
+/// Uebernimmt die gespeicherten Lautstaerken pro Benutzer (z.B. beim Start)
+///
+/// Veraltete Eintraege werden dabei entfernt; zurueck kommt die bereinigte
+/// Tabelle zum Speichern.
+#[tauri::command]
+pub async fn set_user_audio_preferences(
+    state: State<'_, AppState>,
+    config: BenutzerAudioEinstellungen,
+) -> Result<BenutzerAudioEinstellungen, String> {
+    validation::benutzer_audio(&config)?;
+    let entfernt = state.benutzer_pegel.laden(config, jetzt_unix());
+    debug!(
+        "Benutzer-Lautstaerken geladen ({} veraltete entfernt)",
+        entfernt
+    );
+    Ok(state.benutzer_pegel.einstellungen())
+}
+
+/// Setzt die Lautstaerke eines Benutzers (0.0 - 2.0)
+///
+/// Wirkt ab dem naechsten Paket und gilt auch in spaeteren Sitzungen.
+#[tauri::command]
+pub async fn set_user_volume(
+    state: State<'_, AppState>,
+    user_id: String,
+    gain: f32,
+) -> Result<BenutzerAudioEinstellungen, String> {
+    let user_id = validation::benutzer_id(&user_id)?;
+    validation::benutzer_gain(gain)?;
+    state
+        .benutzer_pegel
+        .lautstaerke_setzen(user_id, gain, jetzt_unix());
+    Ok(state.benutzer_pegel.einstellungen())
+}
+
+/// Setzt die Lautstaerke des Benutzers hinter einer SSRC (0.0 - 2.0)
+///
+/// Gespeichert wird pro Benutzer, die Einstellung gilt also auch nach einem
+/// erneuten Beitritt mit neuer SSRC.
+#[tauri::command]
+pub async fn set_ssrc_volume(
+    state: State<'_, AppState>,
+    ssrc: u32,
+    gain: f32,
+) -> Result<BenutzerAudioEinstellungen, String> {
+    validation::benutzer_gain(gain)?;
+    let user_id = state
+        .benutzer_pegel
+        .user_von_ssrc(ssrc)
+        .ok_or_else(|| format!("SSRC {} ist keinem Benutzer zugeordnet", ssrc))?;
+    state
+        .benutzer_pegel
+        .lautstaerke_setzen(user_id, gain, jetzt_unix());
+    Ok(state.benutzer_pegel.einstellungen())
+}
+
+/// Gibt die wirksamen Lautstaerken samt aktueller SSRC zurueck
+#[tauri::command]
+pub async fn get_user_volumes(
+    state: State<'_, AppState>,
+) -> Result<Vec<BenutzerLautstaerke>, String> {
+    Ok(state.benutzer_pegel.lautstaerken())
+}
+
+/// Schaltet einen Benutzer lokal stumm oder wieder laut
+#[tauri::command]
+pub async fn set_user_muted(
+    state: State<'_, AppState>,
+    user_id: String,
+    muted: bool,
+) -> Result<BenutzerAudioEinstellungen, String> {
+    let user_id = validation::benutzer_id(&user_id)?;
+    state
+        .benutzer_pegel
+        .stumm_setzen(user_id, muted, jetzt_unix());
+    Ok(state.benutzer_pegel.einstellungen())
+}
+
+/// Vergisst die Lautstaerke eines Benutzers (ohne `user_id`: aller Benutzer)
+#[tauri::command]
+pub async fn reset_user_audio_preferences(
+    state: State<'_, AppState>,
+    user_id: Option<String>,
+) -> Result<BenutzerAudioEinstellungen, String> {
+    let user_id = user_id
+        .as_deref()
+        .map(validation::benutzer_id)
+        .transpose()?;
+    state.benutzer_pegel.zuruecksetzen(user_id.as_ref());
+    info!(
+        "Benutzer-Lautstaerken zurueckgesetzt ({})",
+        user_id.map_or_else(|| "alle".to_string(), |id| id.to_string())
+    );
+    Ok(state.benutzer_pegel.einstellungen())
+}
+
+/// Exportiert die Client-Einstellungen
+///
+/// Die Lautstaerken pro Benutzer und die Entwuerfe sind nur mit
+/// `include_user_audio` enthalten.
+#[tauri::command]
+pub async fn export_settings(
+    state: State<'_, AppState>,
+    include_user_audio: Option<bool>,
+) -> Result<SettingsExport, String> {
+    let audio = state
+        .audio
+        .lock()
+        .map_err(|e| e.to_string())?
+        .full_settings
+        .clone();
+    let event_sounds = state
+        .event_sounds
+        .lock()
+        .map_err(|e| e.to_string())?
+        .einstellungen()
+        .clone();
+    let qos = state.qos.lock().map_err(|e| e.to_string())?.clone();
+    let hardware_mute = state
+        .hardware_stumm
+        .lock()
+        .map_err(|e| e.to_string())?
+        .clone();
+    let persoenlich = include_user_audio.unwrap_or(false);
+    let user_audio = persoenlich.then(|| state.benutzer_pegel.einstellungen());
+    let drafts = persoenlich.then(|| state.entwuerfe.einstellungen());
+    Ok(SettingsExport {
+        audio,
+        event_sounds: Some(event_sounds),
+        qos: Some(qos),
+        hardware_mute: Some(hardware_mute),
+        user_audio,
+        drafts,
+    })
+}
+
+/// Importiert Client-Einstellungen aus einem Export
+///
+/// Die Lautstaerken pro Benutzer und die Entwuerfe werden nur mit
+/// `include_user_audio` uebernommen. Alle Teile werden vor dem Uebernehmen geprueft; zurueck
+/// kommt der nun gueltige Stand.
+#[tauri::command]
+pub async fn import_settings(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    settings: SettingsExport,
+    include_user_audio: Option<bool>,
+) -> Result<SettingsExport, String> {
+    let include_user_audio = include_user_audio.unwrap_or(false);
+    let user_audio = settings.user_audio.filter(|_| include_user_audio);
+    let drafts = settings.drafts.filter(|_| include_user_audio);
+    if let Some(ref audio) = settings.audio {
+        validation::audio_einstellungen(audio)?;
+    }
+    if let Some(ref qos) = settings.qos {
+        validation::qos(qos)?;
+    }
+    if let Some(ref hardware_mute) = settings.hardware_mute {
+        validation::hardware_stumm(hardware_mute)?;
+    }
+    if let Some(ref user_audio) = user_audio {
+        validation::benutzer_audio(user_audio)?;
+    }
+    if let Some(ref drafts) = drafts {
+        validation::entwuerfe(drafts)?;
+    }
+
+    if let Some(audio) = settings.audio {
+        set_audio_settings(app.clone(), state.clone(), audio).await?;
+    }
+    if let Some(event_sounds) = settings.event_sounds {
+        set_event_sound_settings(state.clone(), event_sounds).await?;
+    }
+    if let Some(qos) = settings.qos {
+        set_qos_settings(state.clone(), qos).await?;
+    }
+    if let Some(hardware_mute) = settings.hardware_mute {
+        set_hardware_mute_settings(state.clone(), hardware_mute).await?;
+    }
+    if let Some(user_audio) = user_audio {
+        state.benutzer_pegel.laden(user_audio, jetzt_unix());
+    }
+    if let Some(drafts) = drafts {
+        state.entwuerfe.laden(drafts);
+    }
+
+    info!(
+        "Einstellungen importiert (Benutzer-Lautstaerken: {})",
+        if include_user_audio { "ja" } else { "nein" }
+    );
+    export_settings(state, Some(include_user_audio)).await
+}
+
+/// Meldet Benutzeraktivitaet an den Server (AFK-Erkennung)
+///
+/// Wird vom Frontend bei echten Eingaben (Tastatur, Maus) aufgerufen – eine
+/// OS-weite Idle-Erkennung steht im Client nicht zur Verfuegung. Meldungen
+/// werden auf hoechstens eine pro Minute gedrosselt.
+#[tauri::command]
+pub async fn report_activity(state: State<'_, AppState>) -> Result<(), String> {
+    let faellig = state
+        .aktivitaet
+        .lock()
+        .map_err(|e| e.to_string())?
+        .faellig(std::time::Instant::now());
+    if !faellig {
+        return Ok(());
+    }
+
+    let mut tcp = state.tcp.lock().await;
+    if let Some(ref mut conn) = *tcp {
+        conn.report_activity()
+            .await
+            .map_err(|e| format!("Aktivitaet konnte nicht gemeldet werden: {}", e))?;
+    }
+    Ok(())
+}
+
+/// Startet den Netzwerk-Debugmodus (Paket-Trace der Voice-Verbindung)
+///
+/// Schreibt pro Voice-Paket eine NDJSON-Zeile nach `path` (rotiert, hoechstens
+/// `max_mb` MB). Audio-Nutzdaten nur mit `include_payload`.
+#[tauri::command]
+pub async fn start_voice_trace(
+    state: State<'_, AppState>,
+    path: String,
+    max_mb: u32,
+    include_payload: Option<bool>,
+) -> Result<(), String> {
+    let pfad = validation::voice_trace(&path, max_mb)?;
+    state.voice_trace.starten(
+        pfad,
+        u64::from(max_mb) * 1024 * 1024,
+        include_payload.unwrap_or(false),
+    )
+}
+
+/// Beendet den Paket-Trace und gibt den Abschlussbericht zurueck
+#[tauri::command]
+pub async fn stop_voice_trace(
+    state: State<'_, AppState>,
+) -> Result<Option<TraceBericht>, String> {
+    let trace = std::sync::Arc::clone(&state.voice_trace);
+    tokio::task::spawn_blocking(move || trace.stoppen())
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Wertet eine Trace-Datei aus (Verlustlaeufe, Umsortierung, Zwischenankunft)
+#[tauri::command]
+pub async fn summarize_voice_trace(path: String) -> Result<TraceZusammenfassung, String> {
+    let pfad = validation::pfad("Trace-Datei", &path, validation::PfadArt::Datei)?;
+    tokio::task::spawn_blocking(move || voice_trace::zusammenfassen(&pfad))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| format!("Trace-Datei konnte nicht gelesen werden: {}", e))
+}
+
+/// Spielt einen Event-Sound ab (z.B. Poke oder Mention aus dem Frontend)
+#[tauri::command]
+pub async fn play_event_sound(
+    state: State<'_, AppState>,
+    event: SoundEreignis,
+) -> Result<(), String> {
+    event_sound_abspielen(&state, event).await;
+    Ok(())
+}
+
 // --- Chat-Datentypen (Phase 4) ---
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -1249,9 +2932,7 @@ pub struct ChatMessage {
 
 /// Hilfsfunktion: parst eine Kanal-ID (UUID)
 fn parse_channel_id(channel_id: &str) -> Result<ChannelId, String> {
-    uuid::Uuid::parse_str(channel_id)
-        .map(ChannelId)
-        .map_err(|_| format!("Ungueltige Kanal-ID '{}': keine gueltige UUID", channel_id))
+    Ok(validation::kanal_id(channel_id)?)
 }
 
 /// Konvertiert einen Unix-Timestamp (Sekunden) in ISO8601
@@ -1260,18 +2941,34 @@ fn unix_timestamp_to_iso(secs: u64) -> String {
     format!("{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z", y, mo, d, h, mi, s)
 }
 
+/// Hoechstlaenge einer Chat-Nachricht: Wert des Servers, ohne Verbindung der
+/// des Protokolls
+fn nachrichten_grenze(state: &AppState) -> Result<usize, String> {
+    let conn = state.connection.lock().map_err(|e| e.to_string())?;
+    Ok(conn.max_message_chars.unwrap_or(validation::MAX_NACHRICHT))
+}
+
 /// Sendet eine Text-Nachricht in einen Kanal via TCP
+///
+/// Bestaetigt der Server die Nachricht, wird der Entwurf des Kanals
+/// verworfen und die Tabelle per [`ENTWUERFE_EREIGNIS`] gemeldet.
 #[tauri::command]
 pub async fn send_message(
+    app: tauri::AppHandle,
     state: State<'_, AppState>,
     channel_id: String,
     content: String,
     reply_to: Option<String>,
-) -> Result<ChatMessage, String> {
+) -> Result<ChatMessage, SendMessageError> {
+    let content = validation::zeilenumbrueche(content);
+    let cid = validation::nachricht_senden(
+        &channel_id,
+        &content,
+        reply_to.as_deref(),
+        nachrichten_grenze(&state)?,
+    )?;
     debug!("Sende Nachricht in Kanal {}", channel_id);
 
-    let cid = parse_channel_id(&channel_id)?;
-
     let mut tcp = state.tcp.lock().await;
     let conn = tcp
         .as_mut()
@@ -1296,6 +2993,11 @@ pub async fn send_message(
             let conn_state = state.connection.lock().map_err(|e| e.to_string())?;
             let sender_name = conn_state.username.clone().unwrap_or_else(|| "Du".to_string());
             drop(conn_state);
+            if let Some(entwuerfe) = state.entwuerfe.bestaetigt(&cid) {
+                if let Err(e) = app.emit(ENTWUERFE_EREIGNIS, entwuerfe) {
+                    debug!("Entwurfs-Event konnte nicht gesendet werden: {}", e);
+                }
+            }
             Ok(ChatMessage {
                 id: resp.message_id,
                 channel_id,
@@ -1309,11 +3011,23 @@ pub async fn send_message(
                 edited_at: None,
             })
         }
-        ControlPayload::Error(e) => Err(format!("Server-Fehler: {}", e.message)),
+        ControlPayload::Error(e) if e.code == ErrorCode::RateLimited => {
+            let retry_after_secs = e
+                .details
+                .as_ref()
+                .and_then(|d| d["retry_after_secs"].as_u64())
+                .unwrap_or(1);
+            Err(SendMessageError::RateLimited {
+                retry_after_secs,
+                message: e.message,
+            })
+        }
+        ControlPayload::Error(e) => Err(format!("Server-Fehler: {}", e.message).into()),
         other => Err(format!(
             "Unerwartete Antwort vom Server: {:?}",
             std::mem::discriminant(&other)
-        )),
+        )
+        .into()),
     }
 }
 
@@ -1325,13 +3039,12 @@ pub async fn get_message_history(
     before: Option<String>,
     limit: Option<u32>,
 ) -> Result<Vec<ChatMessage>, String> {
+    let cid = validation::nachrichten_verlauf(&channel_id, before.as_deref(), limit)?;
     debug!(
         "Lade Nachrichten-History fuer Kanal {} (before={:?}, limit={:?})",
         channel_id, before, limit
     );
 
-    let cid = parse_channel_id(&channel_id)?;
-
     let mut tcp = state.tcp.lock().await;
     let conn = tcp
         .as_mut()
@@ -1344,6 +3057,7 @@ pub async fn get_message_history(
             channel_id: cid,
             before,
             limit: limit.map(|l| l as i64),
+            chunked: false,
         }),
     );
 
@@ -1351,23 +3065,67 @@ pub async fn get_message_history(
 
     match antwort.payload {
         ControlPayload::ChatHistoryResponse(resp) => {
-            let nachrichten: Vec<ChatMessage> = resp
-                .messages
-                .into_iter()
-                .map(|m| ChatMessage {
-                    channel_id: m.channel_id.inner().to_string(),
-                    sender_id: m.sender_id.inner().to_string(),
-                    sender_name: m.sender_id.inner().to_string(),
-                    id: m.message_id,
-                    content: m.content,
-                    message_type: m.message_type,
-                    reply_to: m.reply_to,
-                    file_info: None,
-                    created_at: m.created_at,
-                    edited_at: m.edited_at,
-                })
-                .collect();
-            Ok(nachrichten)
+            Ok(chat_nachrichten_aufloesen(conn, resp.messages).await)
+        }
+        ControlPayload::Error(e) => Err(format!("Server-Fehler: {}", e.message)),
+        other => Err(format!(
+            "Unerwartete Antwort vom Server: {:?}",
+            std::mem::discriminant(&other)
+        )),
+    }
+}
+
+/// Durchsucht die Nachrichten eines Kanals (neueste zuerst)
+///
+/// Nur moeglich, solange man Mitglied des Kanals ist. Fuer weitere Treffer
+/// `created_at` und `id` des letzten Treffers als `before` und `before_id`
+/// uebergeben.
+#[tauri::command]
+pub async fn search_messages(
+    state: State<'_, AppState>,
+    channel_id: String,
+    query: String,
+    before: Option<String>,
+    before_id: Option<String>,
+    limit: Option<u32>,
+) -> Result<Vec<ChatMessage>, String> {
+    let cid = validation::nachrichten_suchen(
+        &channel_id,
+        &query,
+        before.as_deref(),
+        before_id.as_deref(),
+        limit,
+    )?;
+    debug!(
+        "Durchsuche Kanal {} (before={:?}, limit={:?})",
+        channel_id, before, limit
+    );
+
+    let mut tcp = state.tcp.lock().await;
+    let conn = tcp
+        .as_mut()
+        .ok_or_else(|| "Nicht verbunden – bitte zuerst connect_to_server aufrufen".to_string())?;
+
+    let request_id = conn.next_id();
+    let nachricht = speakeasy_protocol::control::ControlMessage::new(
+        request_id,
+        ControlPayload::ChatSearch(ChatSearchRequest {
+            channel_id: cid,
+            query,
+            limit: limit.map(|l| l as i64),
+            before,
+            before_id,
+        }),
+    );
+
+    let antwort = conn
+        .send_and_receive(nachricht)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    match antwort.payload {
+        ControlPayload::ChatSearchResponse(resp) => {
+            Ok(chat_nachrichten_aufloesen(conn, resp.messages).await)
         }
         ControlPayload::Error(e) => Err(format!("Server-Fehler: {}", e.message)),
         other => Err(format!(
@@ -1377,6 +3135,264 @@ pub async fn get_message_history(
     }
 }
 
+/// Abschluss eines schrittweise geladenen Verlaufs
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct HistoryAbschluss {
+    /// Anzahl gelieferter Nachrichten
+    pub total: u32,
+    /// Cursor (`before`) fuer aeltere Nachrichten, None wenn es keine gibt
+    pub next_before: Option<String>,
+}
+
+/// Laedt die Nachrichten-History schrittweise (Teilstuecke ueber `on_chunk`)
+///
+/// Fuer das erste Laden eines Kanals: jedes Teilstueck (aelteste Nachricht
+/// zuerst) kann sofort angezeigt werden, statt auf die ganze Antwort zu
+/// warten.
+#[tauri::command]
+pub async fn stream_message_history(
+    state: State<'_, AppState>,
+    channel_id: String,
+    before: Option<String>,
+    limit: Option<u32>,
+    on_chunk: tauri::ipc::Channel<Vec<ChatMessage>>,
+) -> Result<HistoryAbschluss, String> {
+    let cid = validation::nachrichten_verlauf(&channel_id, before.as_deref(), limit)?;
+    debug!(
+        "Lade Nachrichten-History in Teilstuecken fuer Kanal {} (before={:?}, limit={:?})",
+        channel_id, before, limit
+    );
+
+    let mut tcp = state.tcp.lock().await;
+    let conn = tcp
+        .as_mut()
+        .ok_or_else(|| "Nicht verbunden – bitte zuerst connect_to_server aufrufen".to_string())?;
+
+    let anfrage = ChatHistoryRequest {
+        channel_id: cid,
+        before,
+        limit: limit.map(|l| l as i64),
+        chunked: true,
+    };
+    let abschluss = conn
+        .chat_history_streamen(anfrage, |nachrichten, namen| {
+            let nachrichten = nachrichten
+                .into_iter()
+                .map(|n| chat_nachricht_aus(n, namen))
+                .collect();
+            if let Err(e) = on_chunk.send(nachrichten) {
+                warn!("History-Teilstueck konnte nicht zugestellt werden: {}", e);
+            }
+        })
+        .await
+        .map_err(|e| e.to_string())?;
+
+    Ok(HistoryAbschluss {
+        total: abschluss.total,
+        next_before: abschluss.next_before,
+    })
+}
+
+/// Konvertiert eine Protokoll-Nachricht in das Frontend-DTO
+///
+/// Der Absendername kommt aus `namen`; unbekannte Absender erscheinen mit
+/// ihrer ID (die Oberflaeche loest sie per `resolve_display_names` nach).
+pub fn chat_nachricht_aus(m: ChatMessageInfo, namen: &NamensCache) -> ChatMessage {
+    let sender_name = namen
+        .anzeigename(&m.sender_id)
+        .map(str::to_string)
+        .unwrap_or_else(|| m.sender_id.inner().to_string());
+    ChatMessage {
+        channel_id: m.channel_id.inner().to_string(),
+        sender_id: m.sender_id.inner().to_string(),
+        sender_name,
+        id: m.message_id,
+        content: m.content,
+        message_type: m.message_type,
+        reply_to: m.reply_to,
+        file_info: None,
+        created_at: m.created_at,
+        edited_at: m.edited_at,
+    }
+}
+
+/// Konvertiert Protokoll-Nachrichten, unbekannte Absender vorher nachfragen
+///
+/// Scheitert die Nachfrage (z.B. aeltere Server ohne `ResolveIds`), bleiben
+/// unbekannte Absender bei ihrer ID.
+async fn chat_nachrichten_aufloesen(
+    conn: &mut ServerConnection,
+    nachrichten: Vec<ChatMessageInfo>,
+) -> Vec<ChatMessage> {
+    let absender: Vec<UserId> = nachrichten.iter().map(|n| n.sender_id).collect();
+    if let Err(e) = conn.namen_aufloesen(absender, []).await {
+        warn!("Absendernamen konnten nicht aufgeloest werden: {}", e);
+    }
+    nachrichten
+        .into_iter()
+        .map(|n| chat_nachricht_aus(n, conn.namen()))
+        .collect()
+}
+
+/// IDs fuer `resolve_display_names`
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct NamensAnfrage {
+    #[serde(default)]
+    pub user_ids: Vec<String>,
+    #[serde(default)]
+    pub channel_ids: Vec<String>,
+}
+
+/// Name eines Benutzers fuer die Anzeige
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BenutzerName {
+    pub user_id: String,
+    pub username: String,
+    pub display_name: String,
+    /// Konto geloescht (Namen sind ein Platzhalter des Servers)
+    pub deleted: bool,
+}
+
+/// Name eines Kanals fuer die Anzeige
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct KanalName {
+    pub channel_id: String,
+    pub name: String,
+    /// Kanal geloescht (Name ist ein Platzhalter des Servers)
+    pub deleted: bool,
+}
+
+/// Antwort von `resolve_display_names` (unbekannte IDs fehlen)
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct AufgeloesteNamen {
+    pub users: Vec<BenutzerName>,
+    pub channels: Vec<KanalName>,
+}
+
+/// Loest Benutzer- und Kanal-IDs in Namen fuer die Anzeige auf
+///
+/// Bekannte Namen kommen aus dem Cache der Verbindung (Listen und
+/// Ereignisse), die uebrigen werden gesammelt beim Server nachgefragt.
+/// Geloeschte Konten erscheinen mit `deleted` und einem Platzhalternamen.
+#[tauri::command]
+pub async fn resolve_display_names(
+    state: State<'_, AppState>,
+    ids: NamensAnfrage,
+) -> Result<AufgeloesteNamen, String> {
+    let (user_ids, channel_ids) = validation::namen_aufloesen(&ids.user_ids, &ids.channel_ids)?;
+
+    let mut tcp = state.tcp.lock().await;
+    let conn = tcp
+        .as_mut()
+        .ok_or_else(|| "Nicht verbunden – bitte zuerst connect_to_server aufrufen".to_string())?;
+    conn.namen_aufloesen(user_ids.iter().copied(), channel_ids.iter().copied())
+        .await
+        .map_err(|e| e.to_string())?;
+
+    Ok(namen_aus(conn.namen(), &user_ids, &channel_ids))
+}
+
+/// Sammelt die bekannten Namen zu den IDs (jede ID hoechstens einmal)
+fn namen_aus(
+    namen: &NamensCache,
+    user_ids: &[UserId],
+    channel_ids: &[ChannelId],
+) -> AufgeloesteNamen {
+    let mut gesehen = HashSet::new();
+    let users = user_ids
+        .iter()
+        .filter(|id| gesehen.insert(**id))
+        .filter_map(|id| namen.benutzer(id))
+        .map(|b| BenutzerName {
+            user_id: b.user_id.inner().to_string(),
+            username: b.username.clone(),
+            display_name: namen
+                .anzeigename(&b.user_id)
+                .unwrap_or_default()
+                .to_string(),
+            deleted: b.deleted,
+        })
+        .collect();
+    let mut gesehen = HashSet::new();
+    let channels = channel_ids
+        .iter()
+        .filter(|id| gesehen.insert(**id))
+        .filter_map(|id| namen.kanal(id))
+        .map(|k| KanalName {
+            channel_id: k.channel_id.inner().to_string(),
+            name: k.name.clone(),
+            deleted: k.deleted,
+        })
+        .collect();
+    AufgeloesteNamen { users, channels }
+}
+
+/// Hoechstlaenge einer Chat-Nachricht fuer den Zeichenzaehler des Eingabefelds
+#[tauri::command]
+pub async fn get_message_limit(state: State<'_, AppState>) -> Result<usize, String> {
+    nachrichten_grenze(&state)
+}
+
+/// Speichert den Entwurf eines Kanals (leerer Text entfernt ihn)
+///
+/// Zurueck kommt die Tabelle zum Speichern; zu viele Entwuerfe verdraengen
+/// die aeltesten.
+#[tauri::command]
+pub async fn save_draft(
+    state: State<'_, AppState>,
+    channel_id: String,
+    text: String,
+) -> Result<EntwurfsEinstellungen, String> {
+    let text = validation::zeilenumbrueche(text);
+    let cid = validation::entwurf(&channel_id, &text)?;
+    let verworfen = state.entwuerfe.speichern(cid, text, jetzt_unix());
+    if verworfen > 0 {
+        debug!("{} aeltere Entwuerfe verworfen", verworfen);
+    }
+    Ok(state.entwuerfe.einstellungen())
+}
+
+/// Gibt den Entwurf eines Kanals zurueck (`None` = keiner)
+#[tauri::command]
+pub async fn get_draft(
+    state: State<'_, AppState>,
+    channel_id: String,
+) -> Result<Option<String>, String> {
+    let cid = parse_channel_id(&channel_id)?;
+    Ok(state.entwuerfe.lesen(&cid))
+}
+
+/// Verwirft den Entwurf eines Kanals
+#[tauri::command]
+pub async fn clear_draft(
+    state: State<'_, AppState>,
+    channel_id: String,
+) -> Result<EntwurfsEinstellungen, String> {
+    let cid = parse_channel_id(&channel_id)?;
+    state.entwuerfe.verwerfen(&cid);
+    Ok(state.entwuerfe.einstellungen())
+}
+
+/// Gibt alle Entwuerfe zurueck
+#[tauri::command]
+pub async fn get_drafts(state: State<'_, AppState>) -> Result<EntwurfsEinstellungen, String> {
+    Ok(state.entwuerfe.einstellungen())
+}
+
+/// Uebernimmt die gespeicherten Entwuerfe (z.B. beim Start)
+///
+/// Zurueck kommt die gekuerzte Tabelle zum Speichern.
+#[tauri::command]
+pub async fn set_drafts(
+    state: State<'_, AppState>,
+    config: EntwurfsEinstellungen,
+) -> Result<EntwurfsEinstellungen, String> {
+    validation::entwuerfe(&config)?;
+    let verworfen = state.entwuerfe.laden(config);
+    debug!("Entwuerfe geladen ({} verworfen)", verworfen);
+    Ok(state.entwuerfe.einstellungen())
+}
+
 /// Editiert eine Nachricht via TCP
 #[tauri::command]
 pub async fn edit_message(
@@ -1384,6 +3400,8 @@ pub async fn edit_message(
     message_id: String,
     content: String,
 ) -> Result<ChatMessage, String> {
+    let content = validation::zeilenumbrueche(content);
+    validation::nachricht_bearbeiten(&message_id, &content, nachrichten_grenze(&state)?)?;
     debug!("Editiere Nachricht {}", message_id);
 
     let mut tcp = state.tcp.lock().await;
@@ -1432,6 +3450,7 @@ pub async fn delete_message(
     state: State<'_, AppState>,
     message_id: String,
 ) -> Result<(), String> {
+    validation::id("Nachrichten-ID", &message_id)?;
     debug!("Loesche Nachricht {}", message_id);
 
     let mut tcp = state.tcp.lock().await;
@@ -1462,10 +3481,11 @@ pub async fn delete_message(
     }
 }
 
-/// Initiiert einen Datei-Upload via TCP (Token-Flow)
+/// Laedt eine Datei in einen Kanal hoch
 ///
-/// Sendet FileUpload-Request, erhaelt Upload-Token zurueck.
-/// Die eigentliche Datei-Uebertragung erfolgt via HTTP an die Upload-URL.
+/// Meldet den Upload per TCP an (inkl. SHA-256), erhaelt einen signierten
+/// Link und sendet den Inhalt per HTTP-PUT dorthin. Erfolg wird erst nach
+/// der Bestaetigung des Datei-Servers gemeldet.
 #[tauri::command]
 pub async fn upload_file(
     state: State<'_, AppState>,
@@ -1474,6 +3494,7 @@ pub async fn upload_file(
     mime_type: String,
     data: Vec<u8>,
 ) -> Result<ChatMessage, String> {
+    let cid = validation::datei_upload(&channel_id, &filename, &mime_type, &data)?;
     debug!(
         "Lade Datei '{}' ({} Bytes) in Kanal {} hoch",
         filename,
@@ -1481,7 +3502,6 @@ pub async fn upload_file(
         channel_id
     );
 
-    let cid = parse_channel_id(&channel_id)?;
     let size_bytes = data.len() as u64;
 
     let mut tcp = state.tcp.lock().await;
@@ -1497,7 +3517,7 @@ pub async fn upload_file(
             filename: filename.clone(),
             size_bytes,
             mime_type: Some(mime_type.clone()),
-            checksum: None,
+            checksum: Some(datei_upload::pruefsumme(&data)),
         }),
     );
 
@@ -1505,18 +3525,31 @@ pub async fn upload_file(
 
     match antwort.payload {
         ControlPayload::FileUploadResponse(resp) => {
-            let file_id = resp.file_id.clone();
             let sender_id = conn.user_id().unwrap_or("self").to_string();
+            // Die Uebertragung laeuft per HTTP, die TCP-Verbindung bleibt frei
             drop(tcp);
+            debug!(
+                "Upload-Link erhalten: file_id={}, url={}",
+                resp.file_id, resp.upload_url
+            );
+
+            let client = reqwest::Client::builder()
+                .connect_timeout(std::time::Duration::from_secs(10))
+                .build()
+                .map_err(|e| e.to_string())?;
+            let bestaetigung = datei_upload::hochladen(&client, &resp.upload_url, data)
+                .await
+                .map_err(|e| e.to_string())?;
+            info!(
+                "Datei hochgeladen: file_id={}, message_id={}",
+                bestaetigung.file_id, bestaetigung.message_id
+            );
+
             let conn_state = state.connection.lock().map_err(|e| e.to_string())?;
             let sender_name = conn_state.username.clone().unwrap_or_else(|| "Du".to_string());
             drop(conn_state);
-            info!(
-                "Upload-Token erhalten: file_id={}, url={}",
-                file_id, resp.upload_url
-            );
             Ok(ChatMessage {
-                id: uuid::Uuid::new_v4().to_string(),
+                id: bestaetigung.message_id,
                 channel_id,
                 sender_id,
                 sender_name,
@@ -1524,12 +3557,12 @@ pub async fn upload_file(
                 message_type: "file".to_string(),
                 reply_to: None,
                 file_info: Some(FileInfo {
-                    id: file_id,
+                    id: bestaetigung.file_id,
                     filename,
                     mime_type,
-                    size_bytes: size_bytes as i64,
+                    size_bytes: bestaetigung.size_bytes as i64,
                 }),
-                created_at: chrono_now(),
+                created_at: bestaetigung.created_at,
                 edited_at: None,
             })
         }
@@ -1541,20 +3574,64 @@ pub async fn upload_file(
     }
 }
 
-/// Datei-Download – gibt leere Bytes zurueck (HTTP-Download nicht implementiert)
+/// Laedt eine Datei nach `target_path` herunter
 ///
-/// Das Control-Protokoll uebertraegt keine binaeren Dateidaten direkt.
+/// Fordert per TCP einen signierten Link an und streamt die Datei per HTTP.
+/// Stimmen Groesse oder Checksum nicht, wird die Teildatei verworfen.
+/// Gibt die Anzahl geschriebener Bytes zurueck.
 #[tauri::command]
 pub async fn download_file(
-    _state: State<'_, AppState>,
+    state: State<'_, AppState>,
     file_id: String,
-) -> Result<Vec<u8>, String> {
-    debug!("Lade Datei {} herunter", file_id);
-    warn!(
-        "download_file: HTTP-Download noch nicht implementiert (file_id={})",
-        file_id
+    target_path: String,
+) -> Result<u64, String> {
+    let ziel = validation::datei_download(&file_id, &target_path)?;
+    debug!("Lade Datei {} nach {} herunter", file_id, ziel.display());
+
+    let mut tcp = state.tcp.lock().await;
+    let conn = tcp
+        .as_mut()
+        .ok_or_else(|| "Nicht verbunden – bitte zuerst connect_to_server aufrufen".to_string())?;
+
+    let request_id = conn.next_id();
+    let anfrage = speakeasy_protocol::control::ControlMessage::new(
+        request_id,
+        ControlPayload::FileDownload(FileDownloadRequest {
+            file_id: file_id.clone(),
+        }),
     );
-    Ok(vec![])
+    let antwort = conn.send_and_receive(anfrage).await.map_err(|e| e.to_string())?;
+    // Die Verbindung wird fuer den HTTP-Download nicht gebraucht
+    drop(tcp);
+
+    let link = match antwort.payload {
+        ControlPayload::FileDownloadResponse(link) => link,
+        ControlPayload::Error(e) => {
+            return Err(format!("Server-Fehler beim Download: {}", e.message))
+        }
+        other => {
+            return Err(format!(
+                "Unerwartete Antwort vom Server: {:?}",
+                std::mem::discriminant(&other)
+            ))
+        }
+    };
+    info!(
+        "Download-Link erhalten: file_id={}, {} Bytes, gueltig {}s",
+        link.file_id, link.size_bytes, link.expires_in_secs
+    );
+
+    let client = reqwest::Client::builder()
+        .connect_timeout(std::time::Duration::from_secs(10))
+        .build()
+        .map_err(|e| e.to_string())?;
+    let erwartung = datei_download::Erwartung {
+        size_bytes: link.size_bytes,
+        sha256: link.sha256,
+    };
+    datei_download::herunterladen(&client, &link.download_url, &erwartung, &ziel)
+        .await
+        .map_err(|e| e.to_string())
 }
 
 /// Listet Dateien in einem Kanal auf via TCP
@@ -1613,6 +3690,7 @@ pub async fn change_password(
     old_password: String,
     new_password: String,
 ) -> Result<(), String> {
+    validation::passwort_aendern(&old_password, &new_password)?;
     debug!("Passwort-Aenderung angefordert");
 
     let mut tcp = state.tcp.lock().await;
@@ -1654,6 +3732,7 @@ pub async fn change_nickname(
     state: State<'_, AppState>,
     new_nickname: String,
 ) -> Result<String, String> {
+    validation::nickname(&new_nickname)?;
     debug!("Nickname-Aenderung zu '{}'", new_nickname);
 
     let mut tcp = state.tcp.lock().await;
@@ -1689,6 +3768,7 @@ pub async fn set_away(
     away: bool,
     message: Option<String>,
 ) -> Result<(), String> {
+    validation::abwesenheit(message.as_deref())?;
     debug!("Away-Status: away={}, message={:?}", away, message);
 
     let mut tcp = state.tcp.lock().await;
@@ -1877,13 +3957,13 @@ pub async fn install_plugin(
     state: State<'_, AppState>,
     path: String,
 ) -> Result<PluginInstallResultDto, String> {
-    debug!("Installiere Plugin aus Pfad: {}", path);
-    let pfad = std::path::Path::new(&path);
+    let pfad = validation::pfad("Plugin-Pfad", &path, validation::PfadArt::Verzeichnis)?;
+    debug!("Installiere Plugin aus Pfad: {}", pfad.display());
     let manager = state.plugin_manager.lock().map_err(|e| e.to_string())?;
     let Some(ref mgr) = *manager else {
         return Err("PluginManager nicht initialisiert".to_string());
     };
-    let plugin_id = mgr.plugin_laden(pfad).map_err(|e| e.to_string())?;
+    let plugin_id = mgr.plugin_laden(&pfad).map_err(|e| e.to_string())?;
     let info = mgr
         .plugin_info(plugin_id)
         .ok_or_else(|| "Plugin nach dem Laden nicht gefunden".to_string())?;
@@ -1904,8 +3984,7 @@ pub async fn install_plugin(
 
 /// Hilfsfunktion: String-ID in PluginId konvertieren
 fn parse_plugin_id(id: &str) -> Result<speakeasy_plugin::types::PluginId, String> {
-    let uuid = uuid::Uuid::parse_str(id)
-        .map_err(|e| format!("Ungueltige Plugin-ID '{}': {}", id, e))?;
+    let uuid = validation::uuid("Plugin-ID", id)?;
     Ok(speakeasy_plugin::types::PluginId(uuid))
 }
 
@@ -1919,16 +3998,15 @@ pub async fn create_channel(
     max_clients: Option<u32>,
     parent_id: Option<String>,
 ) -> Result<ChannelInfo, String> {
+    let parent_channel_id = validation::kanal_erstellen(
+        &name,
+        description.as_deref(),
+        password.as_deref(),
+        max_clients,
+        parent_id.as_deref(),
+    )?;
     debug!("Erstelle Channel '{}'", name);
 
-    let parent_channel_id = if let Some(ref pid) = parent_id {
-        let uuid = uuid::Uuid::parse_str(pid)
-            .map_err(|_| format!("Ungueltige parent_id '{}': keine gueltige UUID", pid))?;
-        Some(speakeasy_core::types::ChannelId(uuid))
-    } else {
-        None
-    };
-
     let mut tcp = state.tcp.lock().await;
     let conn = tcp
         .as_mut()
@@ -1959,6 +4037,12 @@ pub async fn create_channel(
                 parent_id: parent_id,
                 clients: vec![],
                 max_clients: max_clients.unwrap_or(0),
+                has_children: false,
+                child_count: 0,
+                emergency_muted: false,
+                slow_mode_secs: 0,
+                edit_window_secs: 0,
+                join_by_approval: false,
             })
         }
         ControlPayload::Error(e) => Err(format!("Server-Fehler: {}", e.message)),
@@ -1978,11 +4062,18 @@ pub async fn edit_channel(
     description: Option<String>,
     password: Option<String>,
     max_clients: Option<u32>,
+    slow_mode_secs: Option<u32>,
+    join_by_approval: Option<bool>,
 ) -> Result<(), String> {
+    let cid = validation::kanal_bearbeiten(
+        &channel_id,
+        name.as_deref(),
+        description.as_deref(),
+        password.as_deref(),
+        max_clients,
+    )?;
     debug!("Bearbeite Channel {}", channel_id);
 
-    let cid = parse_channel_id(&channel_id)?;
-
     let mut tcp = state.tcp.lock().await;
     let conn = tcp
         .as_mut()
@@ -1998,13 +4089,15 @@ pub async fn edit_channel(
             password,
             max_clients,
             sort_order: None,
+            slow_mode_secs,
+            join_by_approval,
         }),
     );
 
     let antwort = conn.send_and_receive(nachricht).await.map_err(|e| e.to_string())?;
 
     match antwort.payload {
-        ControlPayload::ChannelList => {
+        ControlPayload::ChannelList(_) => {
             info!("Channel {} erfolgreich bearbeitet", channel_id);
             Ok(())
         }
@@ -2043,7 +4136,7 @@ pub async fn delete_channel(
     let antwort = conn.send_and_receive(nachricht).await.map_err(|e| e.to_string())?;
 
     match antwort.payload {
-        ControlPayload::ChannelList => {
+        ControlPayload::ChannelList(_) => {
             info!("Channel {} erfolgreich geloescht", channel_id);
             Ok(())
         }
@@ -2055,6 +4148,72 @@ pub async fn delete_channel(
     }
 }
 
+/// Laedt die Unterkanaele eines Kanals nach (grosse Server)
+///
+/// Der Teilbaum landet im Kanalbaum-Cache der Verbindung und ist beim
+/// naechsten `get_server_info` enthalten.
+#[tauri::command]
+pub async fn expand_channel(state: State<'_, AppState>, channel_id: String) -> Result<(), String> {
+    validation::kanal_id(&channel_id)?;
+    debug!("Lade Unterkanaele von {}", channel_id);
+
+    let mut tcp = state.tcp.lock().await;
+    let conn = tcp
+        .as_mut()
+        .ok_or_else(|| "Keine TCP-Verbindung vorhanden".to_string())?;
+    conn.expand_channel(&channel_id)
+        .await
+        .map_err(|e| format!("Unterkanaele konnten nicht geladen werden: {}", e))?;
+    Ok(())
+}
+
+/// Laedt alle Mitglieder eines Kanals
+///
+/// Fuer sehr volle Kanaele, deren Beitrittsantwort nur die zuletzt aktiven
+/// Mitglieder enthielt.
+#[tauri::command]
+pub async fn get_channel_members(
+    state: State<'_, AppState>,
+    channel_id: String,
+) -> Result<Vec<ClientInfo>, String> {
+    validation::kanal_id(&channel_id)?;
+    debug!("Lade Mitglieder von {}", channel_id);
+
+    let mut tcp = state.tcp.lock().await;
+    let conn = tcp
+        .as_mut()
+        .ok_or_else(|| "Keine TCP-Verbindung vorhanden".to_string())?;
+    let mitglieder = conn
+        .get_channel_members(&channel_id)
+        .await
+        .map_err(|e| format!("Mitglieder konnten nicht geladen werden: {}", e))?;
+
+    let my_user_id = conn.user_id().unwrap_or_default().to_string();
+    let sprecher = conn.sprecher();
+    Ok(mitglieder
+        .iter()
+        .map(|c| ClientInfo {
+            id: c.user_id.inner().to_string(),
+            username: if c.display_name.is_empty() {
+                c.username.clone()
+            } else {
+                c.display_name.clone()
+            },
+            is_muted: c.is_input_muted || c.is_muted,
+            is_deafened: c.is_deafened,
+            is_self: c.user_id.inner().to_string() == my_user_id,
+            is_listener: c.listen_only,
+            is_speaking: sprecher.spricht(&c.user_id),
+            last_spoke_at: sprecher.zuletzt_gesprochen_ms(&c.user_id),
+            is_emergency_muted: c
+                .channel_id
+                .as_ref()
+                .is_some_and(|ch| conn.notfall_unterdrueckt(ch, &c.user_id)),
+            is_soundboard: c.soundboard,
+        })
+        .collect())
+}
+
 /// Gibt Server-Informationen zurueck
 #[tauri::command]
 pub async fn get_server_info(state: State<'_, AppState>) -> Result<ServerInfo, String> {
@@ -2098,7 +4257,25 @@ pub async fn get_server_info(state: State<'_, AppState>) -> Result<ServerInfo, S
             vec![]
         }
     };
+    // Waehrend der Abfragen eingetroffene MotdChanged-Ereignisse sind enthalten
+    let motd = conn.motd().cloned();
     let my_user_id = conn.user_id().unwrap_or_default().to_string();
+    let sprecher = conn.sprecher().clone();
+    let notfall_aktiv: HashSet<ChannelId> = channels
+        .iter()
+        .map(|ch| ch.channel_id)
+        .filter(|id| conn.notfall_aktiv(id))
+        .collect();
+    let notfall_unterdrueckt: HashSet<UserId> = clients
+        .iter()
+        .filter(|c| {
+            c.channel_id
+                .as_ref()
+                .is_some_and(|ch| conn.notfall_unterdrueckt(ch, &c.user_id))
+        })
+        .map(|c| c.user_id)
+        .collect();
+    drop(tcp);
 
     let channel_dtos: Vec<ChannelInfo> = channels
         .into_iter()
@@ -2113,6 +4290,11 @@ pub async fn get_server_info(state: State<'_, AppState>) -> Result<ServerInfo, S
                     is_muted: c.is_input_muted || c.is_muted,
                     is_deafened: c.is_deafened,
                     is_self: c.user_id.inner().to_string() == my_user_id,
+                    is_listener: c.listen_only,
+                    is_speaking: sprecher.spricht(&c.user_id),
+                    last_spoke_at: sprecher.zuletzt_gesprochen_ms(&c.user_id),
+                    is_emergency_muted: notfall_unterdrueckt.contains(&c.user_id),
+                    is_soundboard: c.soundboard,
                 })
                 .collect();
 
@@ -2123,10 +4305,25 @@ pub async fn get_server_info(state: State<'_, AppState>) -> Result<ServerInfo, S
                 parent_id: ch.parent_id.map(|p| p.inner().to_string()),
                 clients: channel_clients,
                 max_clients: ch.max_clients.unwrap_or(0),
+                has_children: ch.has_children,
+                child_count: ch.child_count,
+                emergency_muted: notfall_aktiv.contains(&ch.channel_id),
+                slow_mode_secs: ch.slow_mode_secs,
+                edit_window_secs: ch.edit_window_secs,
+                join_by_approval: ch.join_by_approval,
             }
         })
         .collect();
 
+    // Eigenes Mikrofon waehrend einer Notfall-Stummschaltung sperren
+    let selbst_gesperrt = channel_dtos
+        .iter()
+        .flat_map(|ch| ch.clients.iter())
+        .any(|c| c.is_self && c.is_emergency_muted);
+    if let Some(ref client) = *state.voice.lock().await {
+        client.set_emergency_muted(selbst_gesperrt);
+    }
+
     Ok(ServerInfo {
         name: info.name,
         description: info.welcome_message.unwrap_or_default(),
@@ -2135,5 +4332,104 @@ pub async fn get_server_info(state: State<'_, AppState>) -> Result<ServerInfo, S
         online_clients: info.current_clients,
         uptime_secs: info.uptime_secs,
         channels: channel_dtos,
+        motd,
     })
 }
+
+// --- Serverliste: Latenz ---
+
+/// Server fuer die Latenzmessung (Adresse und Voice-Port)
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ServerZiel {
+    pub address: String,
+    pub port: u16,
+}
+
+fn latenz_schluessel(address: &str, port: u16) -> String {
+    format!("{}:{}", address, port)
+}
+
+/// Misst die Latenz zu einem Server per UDP-Ping (ohne Anmeldung)
+#[tauri::command]
+pub async fn measure_server_latency(
+    state: State<'_, AppState>,
+    address: String,
+    port: u16,
+) -> Result<LatenzMessung, String> {
+    validation::latenz_ziel(&address, port)?;
+    let messung = server_ping::messen(
+        &address,
+        port,
+        server_ping::PROBEN,
+        server_ping::ABSTAND,
+        server_ping::ZEITLIMIT,
+    )
+    .await
+    .map_err(|e| format!("Latenzmessung fehlgeschlagen: {}", e))?;
+    state
+        .server_latenzen
+        .lock()
+        .map_err(|e| e.to_string())?
+        .insert(latenz_schluessel(&address, port), messung.clone());
+    Ok(messung)
+}
+
+/// Misst alle Lesezeichen gleichzeitig und speichert die Ergebnisse
+///
+/// Nicht aufloesbare Server fehlen im Ergebnis; ihr alter Messwert wird
+/// entfernt.
+#[tauri::command]
+pub async fn refresh_bookmark_latencies(
+    state: State<'_, AppState>,
+    servers: Vec<ServerZiel>,
+) -> Result<HashMap<String, LatenzMessung>, String> {
+    validation::latenz_ziele(&servers)?;
+
+    let mut messungen = tokio::task::JoinSet::new();
+    for ziel in servers {
+        messungen.spawn(async move {
+            let ergebnis = server_ping::messen(
+                &ziel.address,
+                ziel.port,
+                server_ping::PROBEN,
+                server_ping::ABSTAND,
+                server_ping::ZEITLIMIT,
+            )
+            .await;
+            (ziel, ergebnis)
+        });
+    }
+
+    let mut ergebnisse = HashMap::new();
+    let mut entfernen = Vec::new();
+    while let Some(fertig) = messungen.join_next().await {
+        let (ziel, ergebnis) = fertig.map_err(|e| e.to_string())?;
+        let schluessel = latenz_schluessel(&ziel.address, ziel.port);
+        match ergebnis {
+            Ok(messung) => {
+                ergebnisse.insert(schluessel, messung);
+            }
+            Err(e) => {
+                debug!("Latenzmessung fuer {} fehlgeschlagen: {}", schluessel, e);
+                entfernen.push(schluessel);
+            }
+        }
+    }
+
+    let mut latenzen = state.server_latenzen.lock().map_err(|e| e.to_string())?;
+    for schluessel in &entfernen {
+        latenzen.remove(schluessel);
+    }
+    latenzen.extend(ergebnisse.clone());
+    Ok(ergebnisse)
+}
+
+/// Gibt die zuletzt gemessenen Latenzen zurueck (Schluessel `adresse:port`)
+#[tauri::command]
+pub async fn get_bookmark_latencies(
+    state: State<'_, AppState>,
+) -> Result<HashMap<String, LatenzMessung>, String> {
+    let latenzen = state.server_latenzen.lock().map_err(|e| e.to_string())?;
+    Ok(latenzen.clone())
+}
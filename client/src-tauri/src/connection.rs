@@ -3,19 +3,43 @@
 //! Nutzt den FrameCodec aus speakeasy-protocol fuer das Wire-Format
 //! (u32 BE length + JSON payload). Alle Operationen sind async.
 
-use futures_util::{SinkExt, StreamExt};
+use futures_util::{FutureExt, SinkExt, StreamExt};
+use speakeasy_core::{FehlerCode, SpeakeasyError};
 use speakeasy_protocol::{
+    codec::CodecNegotiationRequest,
     control::{
-        ChannelJoinRequest, ChannelLeaveRequest, ControlMessage, ControlPayload, ErrorCode,
-        ErrorResponse, LoginRequest, LoginResponse, LogoutRequest, ServerInfoResponse,
-        VoiceDisconnectRequest, VoiceInitRequest, VoiceReadyResponse,
+        ChannelEmergencyMuteEvent, ChannelInviteAnswerRequest, ChannelInviteRequest,
+        ChannelInviteResponse, ChannelJoinRequest, ChannelKnockAnswerRequest, ChannelKnockRequest,
+        ChannelKnockResponse, ChannelKnockResultEvent, ChannelLeaveRequest, ChannelMembersRequest,
+        ChatHistoryComplete, ChatHistoryRequest, ChatMessageInfo, ChannelListRequest, ChannelListResponse,
+        ChannelTreeExpandRequest, ClientInfo, ClientUpdateRequest, ControlMessage, ControlPayload,
+        E2EKeyEnvelope, E2EKeyRotationRequiredEvent,
+        LoginRequest, LoginResponse, LogoutRequest, Motd, ServerInfoResponse, SessionExpiryWarningEvent,
+        SoundboardPlaybackEvent, VoiceDisconnectRequest, VoiceInitRequest, VoiceReadyResponse, VoiceStatsReport, VoiceStatsResponse,
+        MAX_NACHRICHT_ZEICHEN,
     },
+    chat_verlauf::{VerlaufEmpfang, VerlaufFehler, VerlaufSchritt},
+    handshake::{self, Inkompatibel, Kompatibel},
+    kanalbaum::KanalbaumCache,
+    namen::NamensCache,
+    qos::{self, QosStatus, SockRef},
+    sprecher::SprecherAnzeige,
+    ssrc::SsrcZuordnung,
+    voice::AudioCodec,
     wire::FrameCodec,
 };
+use speakeasy_core::types::{ChannelId, UserId};
+use std::collections::HashMap;
 use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tokio::net::TcpStream;
+use tokio::sync::mpsc;
 use tokio_util::codec::Framed;
 
+use crate::benutzer_audio::BenutzerPegel;
+use crate::voice_krypto::VoiceSchluessel;
+
 // ---------------------------------------------------------------------------
 // Fehler-Typ
 // ---------------------------------------------------------------------------
@@ -26,11 +50,19 @@ pub enum ConnectionError {
     /// TCP-Verbindung fehlgeschlagen
     Io(std::io::Error),
     /// Server hat mit Fehler geantwortet
-    ServerError { code: ErrorCode, message: String },
+    ServerError { code: FehlerCode, message: String },
     /// Unerwartete Antwort vom Server
     UnexpectedResponse(String),
     /// Nicht verbunden
     NotConnected,
+    /// Keine Antwort innerhalb des Zeitlimits
+    Timeout(String),
+    /// Protokollversionen von Client und Server passen nicht zusammen
+    Inkompatibel(Inkompatibel),
+    /// Die Gegenstelle spricht kein Speakeasy-Protokoll
+    KeinSpeakeasyServer(String),
+    /// Der Client wird beendet; es werden keine Anfragen mehr angenommen
+    Beendet,
 }
 
 impl std::fmt::Display for ConnectionError {
@@ -38,12 +70,18 @@ impl std::fmt::Display for ConnectionError {
         match self {
             ConnectionError::Io(e) => write!(f, "IO-Fehler: {}", e),
             ConnectionError::ServerError { code, message } => {
-                write!(f, "Server-Fehler ({:?}): {}", code, message)
+                write!(f, "Server-Fehler ({}): {}", code, message)
             }
             ConnectionError::UnexpectedResponse(msg) => {
                 write!(f, "Unerwartete Antwort: {}", msg)
             }
             ConnectionError::NotConnected => write!(f, "Nicht mit Server verbunden"),
+            ConnectionError::Timeout(msg) => write!(f, "Zeitlimit ueberschritten: {}", msg),
+            ConnectionError::Inkompatibel(e) => write!(f, "{}", e),
+            ConnectionError::KeinSpeakeasyServer(msg) => {
+                write!(f, "Kein Speakeasy-Server: {}", msg)
+            }
+            ConnectionError::Beendet => write!(f, "Client wird beendet"),
         }
     }
 }
@@ -54,6 +92,28 @@ impl From<std::io::Error> for ConnectionError {
     }
 }
 
+impl From<ConnectionError> for SpeakeasyError {
+    fn from(e: ConnectionError) -> Self {
+        match e {
+            ConnectionError::Io(io) => io.into(),
+            ConnectionError::ServerError { code, message } => SpeakeasyError::Server {
+                code,
+                meldung: message,
+            },
+            ConnectionError::UnexpectedResponse(msg) => SpeakeasyError::UngueltigeNachricht(msg),
+            ConnectionError::NotConnected | ConnectionError::Beendet => {
+                SpeakeasyError::Getrennt(e.to_string())
+            }
+            ConnectionError::Timeout(msg) => SpeakeasyError::Zeitlimit(msg),
+            ConnectionError::Inkompatibel(e) => SpeakeasyError::ProtokollVersion {
+                erwartet: e.client.major,
+                erhalten: e.server.map_or(0, |v| v.major),
+            },
+            ConnectionError::KeinSpeakeasyServer(msg) => SpeakeasyError::UngueltigeNachricht(msg),
+        }
+    }
+}
+
 impl From<ConnectionError> for String {
     fn from(e: ConnectionError) -> Self {
         e.to_string()
@@ -64,6 +124,19 @@ impl From<ConnectionError> for String {
 // ServerConnection
 // ---------------------------------------------------------------------------
 
+/// Anzahl der VoiceInit-Versuche, bevor der Beitritt aufgegeben wird
+const VOICE_INIT_VERSUCHE: u32 = 3;
+/// Wartezeit auf VoiceReady pro Versuch
+const VOICE_INIT_ZEITLIMIT: Duration = Duration::from_secs(3);
+/// Pause vor dem zweiten Versuch; verdoppelt sich mit jedem weiteren
+const VOICE_INIT_BACKOFF_START: Duration = Duration::from_millis(250);
+/// Wartezeit auf den TCP-Verbindungsaufbau
+const VERBINDUNGS_ZEITLIMIT: Duration = Duration::from_secs(10);
+/// Wartezeit auf `Welcome`; danach gilt die Gegenstelle als fremder Dienst
+const HANDSHAKE_ZEITLIMIT: Duration = Duration::from_secs(5);
+/// Abstand der eigenen Pings fuer die RTT-Messung
+const PING_INTERVALL: Duration = Duration::from_secs(5);
+
 /// Echte TCP-Verbindung zum Speakeasy Signaling-Server
 pub struct ServerConnection {
     /// Framed TCP-Stream mit FrameCodec
@@ -74,16 +147,64 @@ pub struct ServerConnection {
     user_id: Option<String>,
     /// Monoton steigender Request-ID Zaehler
     next_request_id: AtomicU32,
+    /// DSCP-Markierung des Control-Sockets
+    qos: QosStatus,
+    /// SSRC -> Benutzer im aktuellen Kanal (nur aus Server-Nachrichten)
+    ssrc_zuordnung: SsrcZuordnung,
+    /// Wer spricht im aktuellen Kanal (nur aus Server-Nachrichten)
+    sprecher: SprecherAnzeige,
+    /// Geladene Kanaele (bei grossen Servern nur Teilbaeume)
+    kanalbaum: KanalbaumCache,
+    /// Namen von Benutzern und Kanaelen fuer die Anzeige
+    namen: NamensCache,
+    /// Notfall-stumm geschaltete Kanaele -> ausgenommene Benutzer
+    notfall: HashMap<ChannelId, Vec<UserId>>,
+    /// Laufende Soundboard-Wiedergaben (Pseudo-Clients mit eigener SSRC)
+    soundboard: HashMap<UserId, ClientInfo>,
+    /// Erhaelt jede Aenderung der SSRC-Zuordnung (Lautstaerke pro Benutzer)
+    benutzer_pegel: Arc<BenutzerPegel>,
+    /// Ziel-Bitrate aus `VoiceQualityUpdate`, geteilt mit der Sende-Schleife
+    ziel_bitrate: Arc<AtomicU32>,
+    /// E2E-Gruppenschluessel aus `E2EKey`, geteilt mit dem Voice-Client
+    voice_schluessel: Arc<VoiceSchluessel>,
+    /// Nachricht des Tages (aus dem Login, aktualisiert durch `MotdChanged`)
+    motd: Option<Motd>,
+    /// Hoechstlaenge einer Chat-Nachricht laut `Welcome` (Zeichen)
+    max_nachricht_zeichen: usize,
+    /// Empfaenger unaufgeforderter Ereignisse fuer die Oberflaeche (Chat)
+    ereignisse: Option<mpsc::UnboundedSender<ControlPayload>>,
+    /// Request-ID eines laufenden `SessionRenew` (ausgeloest durch
+    /// `SessionExpiryWarning`, die Antwort wird als Ereignis verarbeitet)
+    verlaengerung: Option<u32>,
+    /// Request-ID und Sendezeit des offenen eigenen Pings
+    ping_offen: Option<(u32, Instant)>,
+    /// Zeitpunkt des letzten eigenen Pings
+    letzter_ping: Option<Instant>,
+    /// Laufzeit des letzten beantworteten Pings (Control-Verbindung)
+    letzte_rtt: Option<Duration>,
+    /// Gesetzt von `close_gracefully`: neue Anfragen werden abgewiesen
+    beendet: bool,
 }
 
 impl ServerConnection {
     /// Baut eine TCP-Verbindung zum Server auf
-    pub async fn connect(addr: &str, port: u16) -> Result<Self, ConnectionError> {
+    ///
+    /// `dscp` markiert den Control-Socket (z.B. AF31), `None` = unmarkiert.
+    pub async fn connect(addr: &str, port: u16, dscp: Option<u8>) -> Result<Self, ConnectionError> {
         let address = format!("{}:{}", addr, port);
         tracing::info!("Verbinde mit {}", address);
-        let stream = TcpStream::connect(&address).await?;
+        let stream = tokio::time::timeout(VERBINDUNGS_ZEITLIMIT, TcpStream::connect(&address))
+            .await
+            .map_err(|_| {
+                ConnectionError::Timeout(format!("Verbindungsaufbau zu {}", address))
+            })??;
         tracing::info!("TCP-Verbindung hergestellt zu {}", address);
 
+        let qos = qos::markieren(SockRef::from(&stream), dscp);
+        if let QosStatus::Abgelehnt { dscp, grund } = &qos {
+            tracing::warn!("DSCP {} fuer Control-Socket abgelehnt: {}", dscp, grund);
+        }
+
         let framed = Framed::new(stream, FrameCodec::new());
 
         Ok(Self {
@@ -91,63 +212,560 @@ impl ServerConnection {
             session_token: None,
             user_id: None,
             next_request_id: AtomicU32::new(1),
+            qos,
+            ssrc_zuordnung: SsrcZuordnung::neu(),
+            sprecher: SprecherAnzeige::neu(),
+            kanalbaum: KanalbaumCache::neu(),
+            namen: NamensCache::neu(),
+            notfall: HashMap::new(),
+            soundboard: HashMap::new(),
+            benutzer_pegel: Arc::new(BenutzerPegel::neu()),
+            ziel_bitrate: Arc::new(AtomicU32::new(0)),
+            voice_schluessel: Arc::new(VoiceSchluessel::neu()),
+            motd: None,
+            max_nachricht_zeichen: MAX_NACHRICHT_ZEICHEN,
+            ereignisse: None,
+            verlaengerung: None,
+            ping_offen: None,
+            letzter_ping: None,
+            letzte_rtt: None,
+            beendet: false,
         })
     }
 
+    /// Aktuelle Nachricht des Tages (`None` = keine gesetzt)
+    pub fn motd(&self) -> Option<&Motd> {
+        self.motd.as_ref()
+    }
+
+    /// Hoechstlaenge einer Chat-Nachricht in Zeichen (nach dem Handshake
+    /// der Wert des Servers)
+    pub fn max_nachricht_zeichen(&self) -> usize {
+        self.max_nachricht_zeichen
+    }
+
+    /// Teilt die SSRC-Zuordnung ab sofort mit dem Empfangs-Mixer
+    pub fn set_benutzer_pegel(&mut self, pegel: Arc<BenutzerPegel>) {
+        pegel.zuordnung_setzen(&self.ssrc_zuordnung);
+        self.benutzer_pegel = pegel;
+    }
+
+    /// Teilt die vom Server vorgegebene Ziel-Bitrate mit dem Voice-Client
+    pub fn set_ziel_bitrate(&mut self, ziel: Arc<AtomicU32>) {
+        self.ziel_bitrate = ziel;
+    }
+
+    /// Teilt die E2E-Schluessel (eigenes Paar, Gruppenschluessel) mit dem Voice-Client
+    pub fn set_voice_schluessel(&mut self, schluessel: Arc<VoiceSchluessel>) {
+        self.voice_schluessel = schluessel;
+    }
+
+    /// Leitet Chat-Ereignisse des Servers ab sofort an `ereignisse` weiter
+    pub fn set_ereignisse(&mut self, ereignisse: mpsc::UnboundedSender<ControlPayload>) {
+        self.ereignisse = Some(ereignisse);
+    }
+
+    /// Verwirft die SSRC-Zuordnung (Kanal verlassen, Verbindung getrennt)
+    fn zuordnung_leeren(&mut self) {
+        self.ssrc_zuordnung.leeren();
+        self.benutzer_pegel.zuordnung_setzen(&self.ssrc_zuordnung);
+    }
+
+    /// Laufzeit des letzten beantworteten Pings (`None` = noch keine Antwort)
+    pub fn letzte_rtt(&self) -> Option<Duration> {
+        self.letzte_rtt
+    }
+
+    /// Gibt den DSCP-Status des Control-Sockets zurueck
+    pub fn qos_status(&self) -> &QosStatus {
+        &self.qos
+    }
+
+    /// SSRC -> Benutzer-Zuordnung des aktuellen Kanals
+    pub fn ssrc_zuordnung(&self) -> &SsrcZuordnung {
+        &self.ssrc_zuordnung
+    }
+
+    /// Sprechanzeige des aktuellen Kanals
+    pub fn sprecher(&self) -> &SprecherAnzeige {
+        &self.sprecher
+    }
+
+    /// Geladener Teil des Kanalbaums
+    pub fn kanalbaum(&self) -> &KanalbaumCache {
+        &self.kanalbaum
+    }
+
+    /// Bekannte Benutzer- und Kanalnamen
+    pub fn namen(&self) -> &NamensCache {
+        &self.namen
+    }
+
+    /// Ist der Kanal notfall-stumm geschaltet?
+    pub fn notfall_aktiv(&self, channel_id: &ChannelId) -> bool {
+        self.notfall.contains_key(channel_id)
+    }
+
+    /// Verwirft der Server Audio dieses Benutzers im Kanal (Notfall-Stummschaltung)?
+    pub fn notfall_unterdrueckt(&self, channel_id: &ChannelId, user_id: &UserId) -> bool {
+        self.notfall
+            .get(channel_id)
+            .is_some_and(|ausgenommen| !ausgenommen.contains(user_id))
+    }
+
+    /// Uebernimmt eine Notfall-Stummschaltung (Ein/Aus) aus einem Server-Ereignis
+    fn notfall_anwenden(&mut self, event: &ChannelEmergencyMuteEvent) {
+        if event.active {
+            self.notfall.insert(event.channel_id, event.exempt.clone());
+        } else {
+            self.notfall.remove(&event.channel_id);
+        }
+    }
+
+    /// Laufende Soundboard-Wiedergaben in einem Kanal
+    fn soundboard_in(&self, channel_id: ChannelId) -> impl Iterator<Item = &ClientInfo> {
+        self.soundboard
+            .values()
+            .filter(move |c| c.channel_id == Some(channel_id))
+    }
+
+    /// Uebernimmt Start oder Ende einer Soundboard-Wiedergabe
+    fn soundboard_anwenden(&mut self, event: &SoundboardPlaybackEvent) {
+        if event.active {
+            self.soundboard
+                .insert(event.client.user_id, event.client.clone());
+        } else {
+            self.soundboard.remove(&event.client.user_id);
+        }
+    }
+
     /// Generiert die naechste Request-ID
     pub fn next_id(&self) -> u32 {
         self.next_request_id.fetch_add(1, Ordering::Relaxed)
     }
 
     /// Sendet eine ControlMessage und wartet auf die Antwort
+    ///
+    /// Nach `close_gracefully` scheitert jede Anfrage mit
+    /// [`ConnectionError::Beendet`].
     pub async fn send_and_receive(
         &mut self,
         message: ControlMessage,
     ) -> Result<ControlMessage, ConnectionError> {
+        if self.beendet {
+            return Err(ConnectionError::Beendet);
+        }
+        self.anfrage_senden(message).await
+    }
+
+    /// Sendet eine Anfrage ohne Pruefung auf `beendet` (Logout beim Beenden)
+    async fn anfrage_senden(
+        &mut self,
+        message: ControlMessage,
+    ) -> Result<ControlMessage, ConnectionError> {
+        let request_id = message.request_id;
         self.framed.send(message).await?;
+        self.antwort_empfangen(request_id).await
+    }
 
+    /// Wartet auf die Antwort zu `request_id`
+    ///
+    /// Pings werden beantwortet, Ereignisse eingepflegt. Verspaetete
+    /// Antworten auf fruehere, bereits aufgegebene Anfragen (z.B. nach einem
+    /// Zeitlimit) werden verworfen.
+    async fn antwort_empfangen(
+        &mut self,
+        request_id: u32,
+    ) -> Result<ControlMessage, ConnectionError> {
         // Auf Antwort warten (Pings vom Server automatisch beantworten)
         loop {
             match self.framed.next().await {
                 Some(Ok(response)) => {
-                    // Server-Ping automatisch beantworten
-                    if let ControlPayload::Ping(ref ping) = response.payload {
-                        let ts = std::time::SystemTime::now()
-                            .duration_since(std::time::UNIX_EPOCH)
-                            .unwrap_or_default()
-                            .as_millis() as u64;
-                        let pong =
-                            ControlMessage::pong(response.request_id, ping.timestamp_ms, ts);
-                        self.framed.send(pong).await?;
+                    if self.ereignis_verarbeiten(&response).await? {
+                        continue;
+                    }
+                    if response.request_id != 0 && response.request_id != request_id {
+                        tracing::debug!(
+                            erwartet = request_id,
+                            erhalten = response.request_id,
+                            "Verspaetete Antwort verworfen"
+                        );
                         continue;
                     }
                     return Ok(response);
                 }
                 Some(Err(e)) => return Err(ConnectionError::Io(e)),
-                None => {
-                    return Err(ConnectionError::Io(std::io::Error::new(
-                        std::io::ErrorKind::ConnectionReset,
-                        "Verbindung vom Server getrennt",
-                    )))
+                None => return Err(Self::getrennt()),
+            }
+        }
+    }
+
+    /// Verarbeitet bereits eingetroffene Server-Nachrichten, ohne zu warten
+    ///
+    /// Zwischen zwei Anfragen liest sonst niemand von der Verbindung; so
+    /// erreichen Ereignisse wie neue Chat-Nachrichten die Oberflaeche auch,
+    /// wenn der Client gerade nichts anfragt.
+    pub async fn ereignisse_abholen(&mut self) -> Result<(), ConnectionError> {
+        loop {
+            match self.framed.next().now_or_never() {
+                Some(Some(Ok(nachricht))) => {
+                    if !self.ereignis_verarbeiten(&nachricht).await? {
+                        tracing::debug!(
+                            erhalten = nachricht.request_id,
+                            "Antwort ohne offene Anfrage verworfen"
+                        );
+                    }
+                }
+                Some(Some(Err(e))) => return Err(ConnectionError::Io(e)),
+                Some(None) => return Err(Self::getrennt()),
+                None => return Ok(()),
+            }
+        }
+    }
+
+    /// Beantwortet Pings und pflegt Ereignisse ein
+    ///
+    /// Gibt `true` zurueck, wenn die Nachricht damit erledigt ist (also
+    /// keine Antwort auf eine Anfrage sein kann).
+    async fn ereignis_verarbeiten(
+        &mut self,
+        response: &ControlMessage,
+    ) -> Result<bool, ConnectionError> {
+        // Server-Ping automatisch beantworten
+        if let ControlPayload::Ping(ref ping) = response.payload {
+            let ts = std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_millis() as u64;
+            let pong = ControlMessage::pong(response.request_id, ping.timestamp_ms, ts);
+            self.framed.send(pong).await?;
+            return Ok(true);
+        }
+        // Antwort auf den eigenen Ping (Pong oder z.B. Drosselung)
+        if self.ping_offen.map(|(id, _)| id) == Some(response.request_id) {
+            self.ping_abschliessen(response);
+            return Ok(true);
+        }
+        // Antwort auf die selbst ausgeloeste Verlaengerung
+        if self.verlaengerung == Some(response.request_id) {
+            self.verlaengerung_abschliessen(response);
+            return Ok(true);
+        }
+        // Kanalbeitritte und SSRC-/Sprech-Ereignisse pflegen die
+        // Zuordnung und Sprechanzeige, Kanal-Ereignisse den
+        // Kanalbaum (auch fuer Zweige, in denen wir nicht sind);
+        // Namen kommen aus Ereignissen wie aus Antworten (Listen,
+        // ResolveIds); Ereignisse sind nie eine Antwort
+        if self.ssrc_zuordnung.anwenden(&response.payload) {
+            self.benutzer_pegel.zuordnung_setzen(&self.ssrc_zuordnung);
+        }
+        self.sprecher.anwenden(&response.payload);
+        self.kanalbaum.anwenden(&response.payload);
+        self.namen.anwenden(&response.payload);
+        match response.payload {
+            ControlPayload::ChannelEmergencyMuteEvent(ref event) => {
+                self.notfall_anwenden(event);
+            }
+            ControlPayload::MotdChanged(ref event) => {
+                self.motd = event.motd.clone();
+            }
+            ControlPayload::SoundboardPlayback(ref event) => {
+                self.soundboard_anwenden(event);
+            }
+            ControlPayload::ServerAnnouncement(ref hinweis) => {
+                tracing::warn!(
+                    titel = %hinweis.title,
+                    dringlichkeit = ?hinweis.severity,
+                    entwarnung = hinweis.resolved,
+                    "Server-Ankuendigung: {}",
+                    hinweis.message
+                );
+            }
+            ControlPayload::VoiceQualityUpdate(ref update) => {
+                tracing::debug!(
+                    bitrate_kbps = update.target_bitrate_kbps,
+                    grund = ?update.reason,
+                    "Neue Ziel-Bitrate vom Server"
+                );
+                self.ziel_bitrate
+                    .store(update.target_bitrate_kbps.into(), Ordering::Relaxed);
+            }
+            ControlPayload::E2EKey(ref umschlag) => {
+                self.e2e_schluessel_annehmen(umschlag);
+            }
+            ControlPayload::E2EKeyRotationRequired(ref ereignis) => {
+                self.e2e_schluessel_verteilen(ereignis).await?;
+            }
+            ControlPayload::SessionExpiryWarning(ref warnung) => {
+                self.sitzung_verlaengern(warnung).await?;
+            }
+            // Chat-Ereignisse, Kanal-Einstellungen (Langsam-Modus),
+            // An-/Abmeldungen und Umbenennungen anderer Benutzer gehen
+            // unveraendert an die Oberflaeche
+            ControlPayload::ChatMessage(_)
+            | ControlPayload::ChatEdited(_)
+            | ControlPayload::ChatDeleted(_)
+            | ControlPayload::ChatBulkDeleted(_)
+            | ControlPayload::ChannelEdited(_)
+            | ControlPayload::ClientConnected(_)
+            | ControlPayload::ClientDisconnected(_)
+            | ControlPayload::ClientJoinedChannel(_)
+            | ControlPayload::ClientLeftChannel(_)
+            | ControlPayload::ClientUpdated(_) => {
+                if let Some(ereignisse) = &self.ereignisse {
+                    let _ = ereignisse.send(response.payload.clone());
+                }
+            }
+            // Einladungen, Beitrittsanfragen, angekuendigte Trennungen und
+            // Sprechwechsel; als Antwort (request_id != 0) gehoert der
+            // Ausgang dem Aufrufer
+            ControlPayload::ChannelInviteReceived(_)
+            | ControlPayload::ChannelInviteResult(_)
+            | ControlPayload::ChannelKnockReceived(_)
+            | ControlPayload::ChannelKnockResult(_)
+            | ControlPayload::PendingDisconnectNotice(_)
+            | ControlPayload::PendingDisconnectCancelled(_)
+            | ControlPayload::ClientSpeaking(_)
+                if response.request_id == 0 =>
+            {
+                if let Some(ereignisse) = &self.ereignisse {
+                    let _ = ereignisse.send(response.payload.clone());
+                }
+            }
+            ControlPayload::ClientVoiceUpdated(_)
+            | ControlPayload::ClientSpeaking(_)
+            | ControlPayload::ClientMoved(_)
+            | ControlPayload::ClientsMoved(_)
+            | ControlPayload::ChannelTreeChanged(_) => {}
+            _ => return Ok(false),
+        }
+        Ok(true)
+    }
+
+    /// Installiert einen vom Verteiler des Kanals weitergeleiteten Gruppenschluessel
+    fn e2e_schluessel_annehmen(&self, umschlag: &E2EKeyEnvelope) {
+        let Some(user_id) = self.eigene_user_id() else {
+            return;
+        };
+        match self
+            .voice_schluessel
+            .verteilung_annehmen(umschlag, &user_id)
+        {
+            Ok(Some(epoch)) => tracing::debug!(epoch, "E2E-Gruppenschluessel erhalten"),
+            Ok(None) => {}
+            Err(e) => tracing::warn!("E2E-Gruppenschluessel nicht angenommen: {}", e),
+        }
+    }
+
+    /// Erzeugt als Verteiler den Schluessel der neuen Epoch und verteilt ihn
+    ///
+    /// Die Verteilung wird nur bei Fehlern beantwortet; eine solche Antwort
+    /// verwirft der Empfang wie jede Antwort ohne offene Anfrage.
+    async fn e2e_schluessel_verteilen(
+        &mut self,
+        ereignis: &E2EKeyRotationRequiredEvent,
+    ) -> Result<(), ConnectionError> {
+        let umschlag = match self.voice_schluessel.rotation_verteilen(ereignis) {
+            Ok(umschlag) => umschlag,
+            Err(e) => {
+                tracing::warn!(
+                    epoch = ereignis.epoch,
+                    "E2E-Schluessel nicht erzeugt: {}",
+                    e
+                );
+                return Ok(());
+            }
+        };
+        tracing::debug!(
+            epoch = ereignis.epoch,
+            empfaenger = ereignis.members.len(),
+            "Verteile E2E-Gruppenschluessel"
+        );
+        let msg = ControlMessage::new(self.next_id(), ControlPayload::E2EKey(umschlag));
+        self.framed.send(msg).await?;
+        Ok(())
+    }
+
+    /// Beantwortet die Ablaufwarnung des Servers mit `SessionRenew`
+    ///
+    /// Wartet nicht auf die Antwort: die Warnung kann mitten in einer
+    /// anderen Anfrage eintreffen. Die Antwort verarbeitet
+    /// [`verlaengerung_abschliessen`](Self::verlaengerung_abschliessen).
+    async fn sitzung_verlaengern(
+        &mut self,
+        warnung: &SessionExpiryWarningEvent,
+    ) -> Result<(), ConnectionError> {
+        if self.session_token.is_none() || self.verlaengerung.is_some() || self.beendet {
+            return Ok(());
+        }
+        tracing::info!(
+            rest_sek = warnung.expires_in_secs,
+            "Session laeuft bald ab, verlaengere"
+        );
+        let request_id = self.next_id();
+        let msg = ControlMessage::new(request_id, ControlPayload::SessionRenew);
+        self.framed.send(msg).await?;
+        self.verlaengerung = Some(request_id);
+        Ok(())
+    }
+
+    /// Wertet die Antwort auf `SessionRenew` aus
+    ///
+    /// Scheitert die Verlaengerung (z.B. weil die Session inzwischen
+    /// abgelaufen ist), geht der Fehler an die Oberflaeche, damit sie neu
+    /// zur Anmeldung auffordert.
+    fn verlaengerung_abschliessen(&mut self, antwort: &ControlMessage) {
+        self.verlaengerung = None;
+        match &antwort.payload {
+            ControlPayload::SessionRenewResponse(r) => {
+                tracing::info!(laeuft_ab = r.expires_at, "Session verlaengert");
+            }
+            ControlPayload::Error(fehler) => {
+                tracing::warn!("Session-Verlaengerung gescheitert: {}", fehler.message);
+                if let Some(ereignisse) = &self.ereignisse {
+                    let _ = ereignisse.send(antwort.payload.clone());
                 }
             }
+            andere => {
+                tracing::warn!(
+                    "Unerwartete Antwort auf SessionRenew: {:?}",
+                    std::mem::discriminant(andere)
+                );
+            }
+        }
+    }
+
+    /// Sendet einen eigenen Ping, wenn seit dem letzten [`PING_INTERVALL`]
+    /// vergangen ist
+    ///
+    /// Wartet wie die Verlaengerung nicht auf die Antwort; den Pong wertet
+    /// [`ping_abschliessen`](Self::ping_abschliessen) aus. Ein unbeantworteter
+    /// Ping wird nach einem Intervall durch den naechsten ersetzt.
+    pub async fn ping_senden_falls_faellig(&mut self) -> Result<(), ConnectionError> {
+        let jetzt = Instant::now();
+        if self.beendet
+            || self
+                .letzter_ping
+                .is_some_and(|t| jetzt.duration_since(t) < PING_INTERVALL)
+        {
+            return Ok(());
         }
+        let ts = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis() as u64;
+        let request_id = self.next_id();
+        let msg = ControlMessage::ping(request_id, ts);
+        self.framed.send(msg).await?;
+        self.ping_offen = Some((request_id, jetzt));
+        self.letzter_ping = Some(jetzt);
+        Ok(())
+    }
+
+    /// Wertet die Antwort auf den eigenen Ping aus
+    fn ping_abschliessen(&mut self, antwort: &ControlMessage) {
+        let Some((_, gesendet)) = self.ping_offen.take() else {
+            return;
+        };
+        match &antwort.payload {
+            ControlPayload::Pong(_) => self.letzte_rtt = Some(gesendet.elapsed()),
+            andere => tracing::debug!(
+                "Ping nicht beantwortet: {:?}",
+                std::mem::discriminant(andere)
+            ),
+        }
+    }
+
+    /// Eigene User-ID als Typ (nach dem Login)
+    fn eigene_user_id(&self) -> Option<UserId> {
+        self.user_id
+            .as_deref()
+            .and_then(|id| uuid::Uuid::parse_str(id).ok())
+            .map(UserId)
+    }
+
+    /// Fehler fuer eine vom Server geschlossene Verbindung
+    fn getrennt() -> ConnectionError {
+        ConnectionError::Io(std::io::Error::new(
+            std::io::ErrorKind::ConnectionReset,
+            "Verbindung vom Server getrennt",
+        ))
     }
 
     /// Prueft ob die Antwort ein Fehler ist und konvertiert ihn
     fn check_error(response: &ControlMessage) -> Result<(), ConnectionError> {
-        if let ControlPayload::Error(ErrorResponse {
-            code, message, ..
-        }) = &response.payload
-        {
+        if let ControlPayload::Error(fehler) = &response.payload {
             return Err(ConnectionError::ServerError {
-                code: *code,
-                message: message.clone(),
+                code: fehler.fehler_code(),
+                message: fehler.message.clone(),
             });
         }
         Ok(())
     }
 
+    /// Gleicht Protokollversion und Faehigkeiten mit dem Server ab
+    ///
+    /// Muss vor dem Login laufen. `benoetigt` sind die Faehigkeiten, auf die
+    /// die Oberflaeche baut; fehlen sie, steht das in [`Kompatibel::fehlend`].
+    /// Antwortet die Gegenstelle nicht innerhalb von `HANDSHAKE_ZEITLIMIT`
+    /// oder nicht mit gueltigen Frames (HTTP-Server, Datenmuell), endet der
+    /// Abgleich mit [`ConnectionError::KeinSpeakeasyServer`].
+    pub async fn handshake(&mut self, benoetigt: &[&str]) -> Result<Kompatibel, ConnectionError> {
+        let hello = handshake::hello(concat!("speakeasy-client/", env!("CARGO_PKG_VERSION")));
+        let request_id = self.next_id();
+        self.framed
+            .send(ControlMessage::new(
+                request_id,
+                ControlPayload::Hello(hello.clone()),
+            ))
+            .await?;
+
+        let antwort = match tokio::time::timeout(HANDSHAKE_ZEITLIMIT, self.framed.next()).await {
+            Ok(Some(Ok(antwort))) => antwort,
+            Ok(Some(Err(e))) if e.kind() == std::io::ErrorKind::InvalidData => {
+                return Err(ConnectionError::KeinSpeakeasyServer(e.to_string()))
+            }
+            // Aeltere Server trennen bei der unbekannten Nachricht
+            Ok(None) => {
+                return Err(ConnectionError::Inkompatibel(handshake::ohne_handshake(
+                    hello.protocol_version,
+                )))
+            }
+            Ok(Some(Err(e))) if e.kind() == std::io::ErrorKind::ConnectionReset => {
+                return Err(ConnectionError::Inkompatibel(handshake::ohne_handshake(
+                    hello.protocol_version,
+                )))
+            }
+            Ok(Some(Err(e))) => return Err(e.into()),
+            Err(_) => {
+                return Err(ConnectionError::KeinSpeakeasyServer(format!(
+                    "keine Antwort auf Hello innerhalb von {} s",
+                    HANDSHAKE_ZEITLIMIT.as_secs()
+                )))
+            }
+        };
+        Self::check_error(&antwort)?;
+
+        match antwort.payload {
+            ControlPayload::Welcome(welcome) => {
+                let kompatibel = handshake::pruefen(&hello, &welcome, benoetigt)
+                    .map_err(ConnectionError::Inkompatibel)?;
+                tracing::info!(
+                    "Server {} (Protokoll {}), gemeinsame Faehigkeiten: {:?}",
+                    welcome.server_version,
+                    welcome.protocol_version,
+                    kompatibel.gemeinsam
+                );
+                self.max_nachricht_zeichen = welcome.max_message_chars as usize;
+                Ok(kompatibel)
+            }
+            other => Err(ConnectionError::KeinSpeakeasyServer(format!(
+                "Erwartet Welcome, erhalten: {:?}",
+                std::mem::discriminant(&other)
+            ))),
+        }
+    }
+
     /// Login am Server mit Benutzername und Passwort
     pub async fn login(
         &mut self,
@@ -163,6 +781,7 @@ impl ServerConnection {
                 token: None,
                 client_version: env!("CARGO_PKG_VERSION").to_string(),
                 display_name: None,
+                capture_consent: false,
             }),
         );
 
@@ -173,6 +792,7 @@ impl ServerConnection {
             ControlPayload::LoginResponse(login_resp) => {
                 self.session_token = Some(login_resp.session_token.clone());
                 self.user_id = Some(login_resp.user_id.inner().to_string());
+                self.motd = login_resp.motd.clone();
                 tracing::info!(
                     "Login erfolgreich: user_id={}",
                     login_resp.user_id.inner()
@@ -188,16 +808,19 @@ impl ServerConnection {
 
     /// Logout vom Server
     pub async fn logout(&mut self) -> Result<(), ConnectionError> {
+        if self.beendet {
+            return Err(ConnectionError::Beendet);
+        }
+        self.abmelden(None).await
+    }
+
+    async fn abmelden(&mut self, reason: Option<String>) -> Result<(), ConnectionError> {
         let request_id = self.next_id();
-        let msg = ControlMessage::new(
-            request_id,
-            ControlPayload::Logout(LogoutRequest { reason: None }),
-        );
+        let msg = ControlMessage::new(request_id, ControlPayload::Logout(LogoutRequest { reason }));
 
-        let response = self.send_and_receive(msg).await?;
+        let response = self.anfrage_senden(msg).await?;
         Self::check_error(&response)?;
-        self.session_token = None;
-        self.user_id = None;
+        self.sitzung_leeren();
         tracing::info!("Logout erfolgreich");
         Ok(())
     }
@@ -206,26 +829,83 @@ impl ServerConnection {
     pub async fn disconnect(&mut self) {
         // Versuche sauber zu senden, ignoriere Fehler
         let _ = self.framed.close().await;
+        self.sitzung_leeren();
+        tracing::info!("TCP-Verbindung getrennt");
+    }
+
+    /// Beendet die Verbindung geordnet (Fenster geschlossen, App beendet)
+    ///
+    /// Ab sofort scheitern neue und noch wartende Anfragen mit
+    /// [`ConnectionError::Beendet`]. Innerhalb von `frist` werden bereits
+    /// eingetroffene Nachrichten noch verarbeitet, dann geht als letzter
+    /// Frame der Logout raus. Anschliessend wird die Schreibseite
+    /// geschlossen und gelesen, bis auch der Server schliesst – ungelesene
+    /// Daten beim Schliessen kaemen sonst als Reset beim Server an. Nach
+    /// Ablauf der Frist wird ohne Logout geschlossen und der Fehler
+    /// zurueckgegeben.
+    pub async fn close_gracefully(&mut self, frist: Duration) -> Result<(), ConnectionError> {
+        self.beendet = true;
+        let ende = tokio::time::Instant::now() + frist;
+
+        let ergebnis = tokio::time::timeout_at(ende, async {
+            self.ereignisse_abholen().await?;
+            if self.session_token.is_some() {
+                self.abmelden(Some("Client beendet".to_string())).await?;
+            }
+            Ok::<_, ConnectionError>(())
+        })
+        .await
+        .unwrap_or_else(|_| Err(ConnectionError::Timeout("Logout beim Beenden".to_string())));
+
+        let _ = tokio::time::timeout_at(ende, async {
+            let _ = self.framed.close().await;
+            while let Some(Ok(_)) = self.framed.next().await {}
+        })
+        .await;
+        self.sitzung_leeren();
+        tracing::info!("Verbindung beim Beenden geschlossen");
+        ergebnis
+    }
+
+    /// Verwirft Sitzung und alle daraus abgeleiteten Zustaende
+    fn sitzung_leeren(&mut self) {
         self.session_token = None;
         self.user_id = None;
-        tracing::info!("TCP-Verbindung getrennt");
+        self.verlaengerung = None;
+        self.zuordnung_leeren();
+        self.sprecher.leeren();
+        self.kanalbaum.leeren();
+        self.namen.leeren();
+        self.notfall.clear();
+        self.voice_schluessel.vergessen();
     }
 
     /// Kanal beitreten
+    ///
+    /// Gibt den effektiven Modus zurueck: `true` = Nur-Zuhoeren (gewuenscht
+    /// oder vom Server erzwungen, z.B. durch `b_voice_transmit`).
     pub async fn join_channel(
         &mut self,
         channel_id: &str,
-    ) -> Result<(), ConnectionError> {
+        listen_only: bool,
+    ) -> Result<bool, ConnectionError> {
         let request_id = self.next_id();
         let uuid = uuid::Uuid::parse_str(channel_id).map_err(|e| {
             ConnectionError::UnexpectedResponse(format!("Ungueltige Channel-ID: {}", e))
         })?;
         let cid = speakeasy_core::types::ChannelId(uuid);
+        // Der Server meldet eine aktive Notfall-Stummschaltung noch vor der
+        // Antwort; ohne Meldung ist der Kanal frei
+        self.notfall.clear();
+        // Gruppenschluessel gelten nur im bisherigen Kanal; der neue kann
+        // ebenfalls noch vor der Antwort eintreffen
+        self.voice_schluessel.vergessen();
         let msg = ControlMessage::new(
             request_id,
             ControlPayload::ChannelJoin(ChannelJoinRequest {
                 channel_id: cid,
                 password: None,
+                listen_only,
             }),
         );
 
@@ -233,9 +913,17 @@ impl ServerConnection {
         Self::check_error(&response)?;
 
         match response.payload {
-            ControlPayload::ChannelJoinResponse(_) => {
-                tracing::info!("Kanal {} beigetreten", channel_id);
-                Ok(())
+            ControlPayload::ChannelJoinResponse(antwort) => {
+                // Sehr volle Kanaele liefern nur eine Auswahl (siehe
+                // `get_channel_members`)
+                tracing::info!(
+                    listen_only = antwort.listen_only,
+                    mitglieder = antwort.member_count,
+                    teilweise = antwort.members_partial,
+                    "Kanal {} beigetreten",
+                    channel_id
+                );
+                Ok(antwort.listen_only)
             }
             other => Err(ConnectionError::UnexpectedResponse(format!(
                 "Erwartet ChannelJoinResponse, erhalten: {:?}",
@@ -261,10 +949,161 @@ impl ServerConnection {
 
         let response = self.send_and_receive(msg).await?;
         Self::check_error(&response)?;
+        self.zuordnung_leeren();
+        self.sprecher.leeren();
+        self.voice_schluessel.vergessen();
         tracing::info!("Kanal {} verlassen", channel_id);
         Ok(())
     }
 
+    /// Laedt einen verbundenen Benutzer in den eigenen Kanal ein
+    pub async fn invite_to_channel(
+        &mut self,
+        channel_id: &str,
+        user_id: &str,
+        message: Option<String>,
+    ) -> Result<ChannelInviteResponse, ConnectionError> {
+        let request_id = self.next_id();
+        let channel_id = uuid::Uuid::parse_str(channel_id).map_err(|e| {
+            ConnectionError::UnexpectedResponse(format!("Ungueltige Channel-ID: {}", e))
+        })?;
+        let user_id = uuid::Uuid::parse_str(user_id).map_err(|e| {
+            ConnectionError::UnexpectedResponse(format!("Ungueltige User-ID: {}", e))
+        })?;
+        let msg = ControlMessage::new(
+            request_id,
+            ControlPayload::ChannelInvite(ChannelInviteRequest {
+                channel_id: ChannelId(channel_id),
+                target_user_id: UserId(user_id),
+                message,
+            }),
+        );
+
+        let response = self.send_and_receive(msg).await?;
+        Self::check_error(&response)?;
+        match response.payload {
+            ControlPayload::ChannelInviteResponse(antwort) => Ok(antwort),
+            other => Err(ConnectionError::UnexpectedResponse(format!(
+                "Erwartet ChannelInviteResponse, erhalten: {:?}",
+                std::mem::discriminant(&other)
+            ))),
+        }
+    }
+
+    /// Nimmt eine Einladung an und tritt dem Kanal bei
+    ///
+    /// Gibt den Kanal und wie [`Self::join_channel`] den effektiven Modus
+    /// zurueck.
+    pub async fn accept_invite(
+        &mut self,
+        invite_id: &str,
+        listen_only: bool,
+    ) -> Result<(String, bool), ConnectionError> {
+        let request_id = self.next_id();
+        self.notfall.clear();
+        self.voice_schluessel.vergessen();
+        let msg = ControlMessage::new(
+            request_id,
+            ControlPayload::ChannelInviteAnswer(ChannelInviteAnswerRequest {
+                invite_id: invite_id.to_string(),
+                accept: true,
+                listen_only,
+            }),
+        );
+
+        let response = self.send_and_receive(msg).await?;
+        Self::check_error(&response)?;
+        match response.payload {
+            ControlPayload::ChannelJoinResponse(antwort) => {
+                let channel_id = antwort.channel_id.inner().to_string();
+                tracing::info!(
+                    listen_only = antwort.listen_only,
+                    mitglieder = antwort.member_count,
+                    "Einladung angenommen, Kanal {} beigetreten",
+                    channel_id
+                );
+                Ok((channel_id, antwort.listen_only))
+            }
+            other => Err(ConnectionError::UnexpectedResponse(format!(
+                "Erwartet ChannelJoinResponse, erhalten: {:?}",
+                std::mem::discriminant(&other)
+            ))),
+        }
+    }
+
+    /// Lehnt eine Einladung ab
+    pub async fn decline_invite(&mut self, invite_id: &str) -> Result<(), ConnectionError> {
+        let request_id = self.next_id();
+        let msg = ControlMessage::new(
+            request_id,
+            ControlPayload::ChannelInviteAnswer(ChannelInviteAnswerRequest {
+                invite_id: invite_id.to_string(),
+                accept: false,
+                listen_only: false,
+            }),
+        );
+
+        let response = self.send_and_receive(msg).await?;
+        Self::check_error(&response)?;
+        tracing::debug!("Einladung {} abgelehnt", invite_id);
+        Ok(())
+    }
+
+    /// Bittet um Einlass in einen Kanal mit Freigabe
+    pub async fn knock_channel(
+        &mut self,
+        channel_id: &str,
+        message: Option<String>,
+    ) -> Result<ChannelKnockResponse, ConnectionError> {
+        let request_id = self.next_id();
+        let uuid = uuid::Uuid::parse_str(channel_id).map_err(|e| {
+            ConnectionError::UnexpectedResponse(format!("Ungueltige Channel-ID: {}", e))
+        })?;
+        let msg = ControlMessage::new(
+            request_id,
+            ControlPayload::ChannelKnock(ChannelKnockRequest {
+                channel_id: ChannelId(uuid),
+                message,
+            }),
+        );
+
+        let response = self.send_and_receive(msg).await?;
+        Self::check_error(&response)?;
+        match response.payload {
+            ControlPayload::ChannelKnockResponse(antwort) => Ok(antwort),
+            other => Err(ConnectionError::UnexpectedResponse(format!(
+                "Erwartet ChannelKnockResponse, erhalten: {:?}",
+                std::mem::discriminant(&other)
+            ))),
+        }
+    }
+
+    /// Laesst einen Anfragenden ein (`admit`) oder lehnt ihn ab
+    pub async fn answer_knock(
+        &mut self,
+        knock_id: &str,
+        admit: bool,
+    ) -> Result<ChannelKnockResultEvent, ConnectionError> {
+        let request_id = self.next_id();
+        let msg = ControlMessage::new(
+            request_id,
+            ControlPayload::ChannelKnockAnswer(ChannelKnockAnswerRequest {
+                knock_id: knock_id.to_string(),
+                admit,
+            }),
+        );
+
+        let response = self.send_and_receive(msg).await?;
+        Self::check_error(&response)?;
+        match response.payload {
+            ControlPayload::ChannelKnockResult(ergebnis) => Ok(ergebnis),
+            other => Err(ConnectionError::UnexpectedResponse(format!(
+                "Erwartet ChannelKnockResult, erhalten: {:?}",
+                std::mem::discriminant(&other)
+            ))),
+        }
+    }
+
     /// Server-Informationen abrufen
     pub async fn get_server_info(&mut self) -> Result<ServerInfoResponse, ConnectionError> {
         let request_id = self.next_id();
@@ -283,17 +1122,51 @@ impl ServerConnection {
     }
 
     /// Channel-Liste abrufen
+    ///
+    /// Fuehrt die Antwort mit dem Kanalbaum-Cache zusammen und gibt alle
+    /// geladenen Kanaele in Baumreihenfolge zurueck. Grosse Server liefern
+    /// nur Root-Ebene und Pfad zum eigenen Kanal (siehe `expand_channel`).
     pub async fn get_channel_list(
         &mut self,
     ) -> Result<Vec<speakeasy_protocol::control::ChannelInfo>, ConnectionError> {
         let request_id = self.next_id();
-        let msg = ControlMessage::new(request_id, ControlPayload::ChannelList);
+        let msg = ControlMessage::new(
+            request_id,
+            ControlPayload::ChannelList(ChannelListRequest::default()),
+        );
 
-        let response = self.send_and_receive(msg).await?;
-        Self::check_error(&response)?;
+        let liste = Self::kanalliste(self.send_and_receive(msg).await?)?;
+        self.kanalbaum.liste_uebernehmen(&liste);
+        Ok(self.kanalbaum.in_baumreihenfolge().into_iter().cloned().collect())
+    }
 
+    /// Unterkanaele eines Kanals nachladen
+    pub async fn expand_channel(
+        &mut self,
+        channel_id: &str,
+    ) -> Result<Vec<speakeasy_protocol::control::ChannelInfo>, ConnectionError> {
+        let request_id = self.next_id();
+        let uuid = uuid::Uuid::parse_str(channel_id).map_err(|e| {
+            ConnectionError::UnexpectedResponse(format!("Ungueltige Channel-ID: {}", e))
+        })?;
+        let msg = ControlMessage::new(
+            request_id,
+            ControlPayload::ChannelTreeExpand(ChannelTreeExpandRequest {
+                channel_id: speakeasy_core::types::ChannelId(uuid),
+                depth: None,
+            }),
+        );
+
+        let teilbaum = Self::kanalliste(self.send_and_receive(msg).await?)?;
+        self.kanalbaum.teilbaum_uebernehmen(&teilbaum);
+        Ok(self.kanalbaum.in_baumreihenfolge().into_iter().cloned().collect())
+    }
+
+    /// Prueft die Antwort und entnimmt die Kanalliste
+    fn kanalliste(response: ControlMessage) -> Result<ChannelListResponse, ConnectionError> {
+        Self::check_error(&response)?;
         match response.payload {
-            ControlPayload::ChannelListResponse(list) => Ok(list.channels),
+            ControlPayload::ChannelListResponse(liste) => Ok(liste),
             other => Err(ConnectionError::UnexpectedResponse(format!(
                 "Erwartet ChannelListResponse, erhalten: {:?}",
                 std::mem::discriminant(&other)
@@ -312,7 +1185,10 @@ impl ServerConnection {
         Self::check_error(&response)?;
 
         match response.payload {
-            ControlPayload::ClientListResponse(list) => Ok(list.clients),
+            ControlPayload::ClientListResponse(mut list) => {
+                list.clients.extend(self.soundboard.values().cloned());
+                Ok(list.clients)
+            }
             other => Err(ConnectionError::UnexpectedResponse(format!(
                 "Erwartet ClientListResponse, erhalten: {:?}",
                 std::mem::discriminant(&other)
@@ -320,24 +1196,140 @@ impl ServerConnection {
         }
     }
 
+    /// Fragt die Namen unbekannter Benutzer und Kanaele beim Server nach
+    ///
+    /// Bereits bekannte IDs werden nicht erneut angefragt, viele IDs in
+    /// mehreren `ResolveIds` verteilt. Die Namen landen im Cache
+    /// (siehe [`Self::namen`]).
+    pub async fn namen_aufloesen(
+        &mut self,
+        user_ids: impl IntoIterator<Item = UserId>,
+        channel_ids: impl IntoIterator<Item = ChannelId>,
+    ) -> Result<(), ConnectionError> {
+        for anfrage in self.namen.fehlende(user_ids, channel_ids) {
+            let request_id = self.next_id();
+            let response = self
+                .send_and_receive(ControlMessage::new(
+                    request_id,
+                    ControlPayload::ResolveIds(anfrage),
+                ))
+                .await?;
+            Self::check_error(&response)?;
+            if !matches!(response.payload, ControlPayload::ResolveIdsResponse(_)) {
+                return Err(ConnectionError::UnexpectedResponse(format!(
+                    "Erwartet ResolveIdsResponse, erhalten: {:?}",
+                    std::mem::discriminant(&response.payload)
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Alle Mitglieder eines Kanals seitenweise abrufen
+    ///
+    /// Fuer Kanaele, deren Beitrittsantwort gekuerzt war. Wer waehrend des
+    /// Abrufs beitritt oder geht, kann fehlen bzw. zusaetzlich enthalten sein.
+    pub async fn get_channel_members(
+        &mut self,
+        channel_id: &str,
+    ) -> Result<Vec<speakeasy_protocol::control::ClientInfo>, ConnectionError> {
+        let uuid = uuid::Uuid::parse_str(channel_id).map_err(|e| {
+            ConnectionError::UnexpectedResponse(format!("Ungueltige Channel-ID: {}", e))
+        })?;
+        let cid = ChannelId(uuid);
+
+        let mut mitglieder = Vec::new();
+        let mut after = None;
+        loop {
+            let request_id = self.next_id();
+            let msg = ControlMessage::new(
+                request_id,
+                ControlPayload::ChannelMembers(ChannelMembersRequest {
+                    channel_id: cid,
+                    after,
+                    limit: None,
+                }),
+            );
+            let response = self.send_and_receive(msg).await?;
+            Self::check_error(&response)?;
+            let seite = match response.payload {
+                ControlPayload::ChannelMembersResponse(seite) => seite,
+                other => {
+                    return Err(ConnectionError::UnexpectedResponse(format!(
+                        "Erwartet ChannelMembersResponse, erhalten: {:?}",
+                        std::mem::discriminant(&other)
+                    )))
+                }
+            };
+            if mitglieder.is_empty() {
+                mitglieder.reserve(seite.total as usize);
+            }
+            mitglieder.extend(seite.clients);
+            match seite.next_after {
+                Some(cursor) => after = Some(cursor),
+                None => {
+                    mitglieder.extend(self.soundboard_in(cid).cloned());
+                    return Ok(mitglieder);
+                }
+            }
+        }
+    }
+
     /// Voice-Init: UDP Port Negotiation mit dem Server
     ///
-    /// Sendet den lokalen UDP-Port und empfaengt Server-UDP-Adresse + SSRC.
+    /// Sendet den lokalen UDP-Port und den bevorzugten Codec und empfaengt
+    /// Server-UDP-Adresse, SSRC und den akzeptierten Codec. Mit
+    /// `codec_negotiation` liefert VoiceReady auch die vereinbarte
+    /// Opus-Konfiguration.
+    ///
+    /// Bleibt VoiceReady aus, wird die Anfrage mit Backoff wiederholt. Der
+    /// Server setzt dabei die bestehende Sitzung fort (`force_new: false`),
+    /// sodass keine zweite SSRC vergeben wird.
     pub async fn voice_init(
         &mut self,
         client_udp_port: u16,
+        codec: AudioCodec,
+        codec_negotiation: Option<CodecNegotiationRequest>,
     ) -> Result<VoiceReadyResponse, ConnectionError> {
-        let request_id = self.next_id();
-        let msg = ControlMessage::new(
-            request_id,
-            ControlPayload::VoiceInit(VoiceInitRequest {
-                client_udp_port,
-                preferred_codec: "opus".to_string(),
-                dtls_fingerprint: None,
-            }),
-        );
+        let mut backoff = VOICE_INIT_BACKOFF_START;
+        let mut versuch = 1;
+        let response = loop {
+            let request_id = self.next_id();
+            let msg = ControlMessage::new(
+                request_id,
+                ControlPayload::VoiceInit(VoiceInitRequest {
+                    client_udp_port,
+                    preferred_codec: codec.name().to_string(),
+                    dtls_fingerprint: None,
+                    force_new: false,
+                    resequencing: true,
+                    e2e_public_key: Some(
+                        self.voice_schluessel.oeffentlicher_schluessel().to_string(),
+                    ),
+                    codec_negotiation: codec_negotiation.clone(),
+                }),
+            );
 
-        let response = self.send_and_receive(msg).await?;
+            match tokio::time::timeout(VOICE_INIT_ZEITLIMIT, self.send_and_receive(msg)).await {
+                Ok(ergebnis) => break ergebnis?,
+                Err(_) if versuch < VOICE_INIT_VERSUCHE => {
+                    tracing::warn!(
+                        versuch,
+                        wiederholung_in_ms = backoff.as_millis() as u64,
+                        "Keine VoiceReady-Antwort, wiederhole Voice-Init"
+                    );
+                    tokio::time::sleep(backoff).await;
+                    backoff *= 2;
+                    versuch += 1;
+                }
+                Err(_) => {
+                    return Err(ConnectionError::Timeout(format!(
+                        "keine VoiceReady-Antwort nach {} Versuchen",
+                        VOICE_INIT_VERSUCHE
+                    )))
+                }
+            }
+        };
         Self::check_error(&response)?;
 
         match response.payload {
@@ -371,6 +1363,123 @@ impl ServerConnection {
         Ok(())
     }
 
+    /// Wechselt im aktuellen Kanal zwischen Senden und Nur-Zuhoeren
+    ///
+    /// Senden erfordert `b_voice_transmit`; sonst antwortet der Server mit
+    /// `PermissionDenied`.
+    pub async fn set_transmit(&mut self, senden: bool) -> Result<(), ConnectionError> {
+        let msg = ControlMessage::new(
+            self.next_id(),
+            ControlPayload::ClientUpdate(ClientUpdateRequest {
+                display_name: None,
+                is_input_muted: None,
+                is_output_muted: None,
+                transmit_requested: Some(senden),
+            }),
+        );
+        let response = self.send_and_receive(msg).await?;
+        Self::check_error(&response)
+    }
+
+    /// Meldet dem Server, ob das Mikrofon stumm ist (eigene oder
+    /// Hardware-Stummschaltung)
+    pub async fn set_input_muted(&mut self, stumm: bool) -> Result<(), ConnectionError> {
+        let msg = ControlMessage::new(
+            self.next_id(),
+            ControlPayload::ClientUpdate(ClientUpdateRequest {
+                display_name: None,
+                is_input_muted: Some(stumm),
+                is_output_muted: None,
+                transmit_requested: None,
+            }),
+        );
+        let response = self.send_and_receive(msg).await?;
+        Self::check_error(&response)
+    }
+
+    /// Meldet Benutzeraktivitaet fuer die serverseitige AFK-Erkennung
+    ///
+    /// Der Server beantwortet `ClientActivity` nicht, daher wird nur gesendet.
+    pub async fn report_activity(&mut self) -> Result<(), ConnectionError> {
+        let msg = ControlMessage::new(self.next_id(), ControlPayload::ClientActivity);
+        self.framed.send(msg).await?;
+        Ok(())
+    }
+
+    /// Voice-Statistik austauschen: sendet die Downlink-Sicht des Clients und
+    /// erhaelt die Empfangsstatistik des Servers fuer den eigenen Stream
+    pub async fn voice_stats(
+        &mut self,
+        report: VoiceStatsReport,
+    ) -> Result<VoiceStatsResponse, ConnectionError> {
+        let request_id = self.next_id();
+        let msg = ControlMessage::new(request_id, ControlPayload::VoiceStats(report));
+
+        let response = self.send_and_receive(msg).await?;
+        Self::check_error(&response)?;
+
+        match response.payload {
+            ControlPayload::VoiceStatsResponse(resp) => Ok(resp),
+            other => Err(ConnectionError::UnexpectedResponse(format!(
+                "Erwartet VoiceStatsResponse, erhalten: {:?}",
+                std::mem::discriminant(&other)
+            ))),
+        }
+    }
+
+    /// Chat-Verlauf in Teilstuecken laden
+    ///
+    /// Jedes Teilstueck (aelteste Nachricht zuerst) geht sofort an
+    /// `teilstueck`, damit die Oberflaeche schrittweise anzeigen kann.
+    /// Dazu kommen die bis dahin bekannten Namen; waehrend des Empfangs wird
+    /// nichts nachgefragt.
+    /// Antwortet der Server ohne Teilstuecke, kommt der ganze Verlauf als
+    /// ein Teilstueck. Bricht der Server mit einem Fehler ab, endet der
+    /// Empfang mit diesem Fehler; bereits gelieferte Teilstuecke bleiben gueltig.
+    pub async fn chat_history_streamen(
+        &mut self,
+        mut request: ChatHistoryRequest,
+        mut teilstueck: impl FnMut(Vec<ChatMessageInfo>, &NamensCache),
+    ) -> Result<ChatHistoryComplete, ConnectionError> {
+        request.chunked = true;
+        let channel_id = request.channel_id;
+        let request_id = self.next_id();
+        self.framed
+            .send(ControlMessage::new(
+                request_id,
+                ControlPayload::ChatHistory(request),
+            ))
+            .await?;
+
+        let mut empfang = VerlaufEmpfang::neu();
+        loop {
+            let response = self.antwort_empfangen(request_id).await?;
+            match empfang.annehmen(response.payload) {
+                Ok(VerlaufSchritt::Teilstueck(teil)) => teilstueck(teil.messages, &self.namen),
+                Ok(VerlaufSchritt::Fertig(abschluss)) => return Ok(abschluss),
+                Ok(VerlaufSchritt::Gesamt(antwort)) => {
+                    // Ohne Cursor vom Server: ab der aeltesten Nachricht weiter
+                    let total = antwort.messages.len() as u32;
+                    let next_before = antwort.messages.first().map(|n| n.created_at.clone());
+                    teilstueck(antwort.messages, &self.namen);
+                    return Ok(ChatHistoryComplete {
+                        channel_id,
+                        chunks: 1,
+                        total,
+                        next_before,
+                    });
+                }
+                Err(VerlaufFehler::Server(fehler)) => {
+                    return Err(ConnectionError::ServerError {
+                        code: fehler.fehler_code(),
+                        message: fehler.message,
+                    })
+                }
+                Err(e) => return Err(ConnectionError::UnexpectedResponse(e.to_string())),
+            }
+        }
+    }
+
     /// Session-Token zurueckgeben
     pub fn session_token(&self) -> Option<&str> {
         self.session_token.as_deref()
@@ -381,3 +1490,195 @@ impl ServerConnection {
         self.user_id.as_deref()
     }
 }
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use speakeasy_protocol::control::{LogoutResponse, PingMessage};
+    use tokio::net::TcpListener;
+
+    /// Was der Test-Server gesehen hat: Anfragen und wie die Verbindung endete
+    type Beobachtung = (Vec<&'static str>, Result<(), std::io::ErrorKind>);
+
+    /// Minimaler Server fuer eine Verbindung
+    ///
+    /// Beantwortet den Logout (wenn `logout_beantworten`) und schickt direkt
+    /// danach noch ein Ereignis hinterher, das der Client beim Schliessen
+    /// ungelesen haben koennte.
+    async fn test_server(logout_beantworten: bool) -> (u16, tokio::task::JoinHandle<Beobachtung>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let server = tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut framed = Framed::new(stream, FrameCodec::new());
+            let mut empfangen = Vec::new();
+            loop {
+                match framed.next().await {
+                    Some(Ok(nachricht)) => match nachricht.payload {
+                        ControlPayload::Logout(_) => {
+                            empfangen.push("logout");
+                            if logout_beantworten {
+                                let antwort = ControlMessage::new(
+                                    nachricht.request_id,
+                                    ControlPayload::LogoutResponse(LogoutResponse { success: true }),
+                                );
+                                framed.send(antwort).await.unwrap();
+                                let ping = ControlMessage::new(
+                                    0,
+                                    ControlPayload::Ping(PingMessage { timestamp_ms: 1 }),
+                                );
+                                framed.send(ping).await.unwrap();
+                            }
+                        }
+                        _ => empfangen.push("andere"),
+                    },
+                    Some(Err(e)) => return (empfangen, Err(e.kind())),
+                    None => return (empfangen, Ok(())),
+                }
+            }
+        });
+        (port, server)
+    }
+
+    #[tokio::test]
+    async fn beenden_meldet_sauber_ab() {
+        let (port, server) = test_server(true).await;
+        let mut conn = ServerConnection::connect("127.0.0.1", port, None).await.unwrap();
+        conn.session_token = Some("sitzung".to_string());
+
+        conn.close_gracefully(Duration::from_secs(2)).await.unwrap();
+        assert!(conn.session_token().is_none());
+
+        // Der Server sieht den Logout als letzten Frame und ein geordnetes
+        // Ende statt eines Resets
+        let (empfangen, ende) = server.await.unwrap();
+        assert_eq!(empfangen, ["logout"]);
+        assert_eq!(ende, Ok(()));
+
+        // Weitere Anfragen werden abgewiesen
+        let anfrage = ControlMessage::new(conn.next_id(), ControlPayload::ServerInfo);
+        assert!(matches!(
+            conn.send_and_receive(anfrage).await,
+            Err(ConnectionError::Beendet)
+        ));
+        assert!(matches!(conn.logout().await, Err(ConnectionError::Beendet)));
+    }
+
+    #[tokio::test]
+    async fn beenden_ohne_sitzung_schliesst_nur() {
+        let (port, server) = test_server(true).await;
+        let mut conn = ServerConnection::connect("127.0.0.1", port, None).await.unwrap();
+
+        conn.close_gracefully(Duration::from_secs(2)).await.unwrap();
+        let (empfangen, ende) = server.await.unwrap();
+        assert!(empfangen.is_empty());
+        assert_eq!(ende, Ok(()));
+    }
+
+    #[tokio::test]
+    async fn ablaufwarnung_loest_verlaengerung_aus_und_meldet_fehlschlag() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let server = tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut framed = Framed::new(stream, FrameCodec::new());
+            let warnung = ControlMessage::new(
+                0,
+                ControlPayload::SessionExpiryWarning(SessionExpiryWarningEvent {
+                    expires_in_secs: 30,
+                }),
+            );
+            framed.send(warnung).await.unwrap();
+            // Der Cleanup war schneller: die Verlaengerung scheitert
+            let anfrage = framed.next().await.unwrap().unwrap();
+            assert!(matches!(anfrage.payload, ControlPayload::SessionRenew));
+            let abgelehnt =
+                ControlMessage::fehler(anfrage.request_id, SpeakeasyError::SessionAbgelaufen);
+            framed.send(abgelehnt).await.unwrap();
+            while let Some(Ok(_)) = framed.next().await {}
+        });
+
+        let mut conn = ServerConnection::connect("127.0.0.1", port, None).await.unwrap();
+        conn.session_token = Some("sitzung".to_string());
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        conn.set_ereignisse(tx);
+
+        let gemeldet = tokio::time::timeout(Duration::from_secs(2), async {
+            loop {
+                conn.ereignisse_abholen().await.unwrap();
+                if let Ok(payload) = rx.try_recv() {
+                    return payload;
+                }
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .expect("Fehlschlag der Verlaengerung nicht gemeldet");
+        let ControlPayload::Error(fehler) = gemeldet else {
+            panic!("Erwartet Error, erhalten: {:?}", gemeldet);
+        };
+        assert_eq!(fehler.fehler_code(), FehlerCode::SessionUngueltig);
+        assert!(conn.verlaengerung.is_none());
+
+        conn.disconnect().await;
+        server.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn eigener_ping_misst_rtt() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let server = tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut framed = Framed::new(stream, FrameCodec::new());
+            let anfrage = framed.next().await.unwrap().unwrap();
+            let ControlPayload::Ping(ref ping) = anfrage.payload else {
+                panic!("Erwartet Ping, erhalten: {:?}", anfrage.payload);
+            };
+            let pong = ControlMessage::pong(anfrage.request_id, ping.timestamp_ms, 0);
+            framed.send(pong).await.unwrap();
+            while let Some(Ok(_)) = framed.next().await {}
+        });
+
+        let mut conn = ServerConnection::connect("127.0.0.1", port, None).await.unwrap();
+        assert!(conn.letzte_rtt().is_none());
+        conn.ping_senden_falls_faellig().await.unwrap();
+        let offen = conn.ping_offen;
+        assert!(offen.is_some());
+
+        // Innerhalb des Intervalls kein weiterer Ping
+        conn.ping_senden_falls_faellig().await.unwrap();
+        assert_eq!(conn.ping_offen, offen);
+
+        tokio::time::timeout(Duration::from_secs(2), async {
+            while conn.letzte_rtt().is_none() {
+                conn.ereignisse_abholen().await.unwrap();
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .expect("Pong nicht ausgewertet");
+        assert!(conn.ping_offen.is_none());
+
+        conn.disconnect().await;
+        server.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn beenden_haelt_die_frist_ein() {
+        // Der Server beantwortet den Logout nie
+        let (port, _server) = test_server(false).await;
+        let mut conn = ServerConnection::connect("127.0.0.1", port, None).await.unwrap();
+        conn.session_token = Some("sitzung".to_string());
+
+        let start = std::time::Instant::now();
+        let ergebnis = conn.close_gracefully(Duration::from_millis(200)).await;
+        assert!(matches!(ergebnis, Err(ConnectionError::Timeout(_))));
+        assert!(start.elapsed() < Duration::from_secs(1));
+        assert!(conn.session_token().is_none());
+    }
+}
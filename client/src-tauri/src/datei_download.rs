@@ -0,0 +1,240 @@
+//! Datei-Download – Dateianhaenge ueber signierte HTTP-Links laden
+//!
+//! Der Server liefert per `FileDownloadResponse` einen zeitlich begrenzten
+//! Link samt erwarteter Groesse und SHA-256. Der Inhalt wird in eine
+//! `.part`-Datei neben dem Ziel gestreamt und dabei gehasht; erst wenn
+//! Groesse und Checksum stimmen, wird sie umbenannt. In allen Fehlerfaellen
+//! wird die Teildatei entfernt.
+
+use std::io;
+use std::path::{Path, PathBuf};
+
+use reqwest::StatusCode;
+use sha2::{Digest, Sha256};
+use tokio::fs::File;
+use tokio::io::AsyncWriteExt;
+
+/// Was der Server ueber die Datei angekuendigt hat
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Erwartung {
+    pub size_bytes: u64,
+    /// SHA-256 (hex)
+    pub sha256: String,
+}
+
+/// Fehler beim Herunterladen
+#[derive(Debug, thiserror::Error)]
+pub enum DownloadFehler {
+    #[error("Download-Link abgelaufen, bitte erneut herunterladen")]
+    Abgelaufen,
+    #[error("Datei wurde geloescht oder existiert nicht mehr")]
+    NichtGefunden,
+    #[error("Download-Link wurde vom Server abgelehnt")]
+    Abgelehnt,
+    #[error("Server antwortete mit HTTP {0}")]
+    Http(u16),
+    #[error("Verbindung zum Datei-Server fehlgeschlagen: {0}")]
+    Netzwerk(#[from] reqwest::Error),
+    #[error("Unerwartete Dateigroesse: {erhalten} statt {erwartet} Bytes")]
+    Groesse { erwartet: u64, erhalten: u64 },
+    #[error("Pruefsumme stimmt nicht, Download verworfen")]
+    Pruefsumme,
+    #[error("Zieldatei konnte nicht geschrieben werden: {0}")]
+    Io(#[from] io::Error),
+}
+
+/// Laedt `url` nach `ziel` und prueft Groesse und Checksum
+///
+/// Gibt die Anzahl geschriebener Bytes zurueck.
+pub async fn herunterladen(
+    client: &reqwest::Client,
+    url: &str,
+    erwartung: &Erwartung,
+    ziel: &Path,
+) -> Result<u64, DownloadFehler> {
+    let teil = teildatei(ziel);
+    let ergebnis = streamen(client, url, erwartung, &teil).await;
+    match ergebnis {
+        Ok(bytes) => {
+            tokio::fs::rename(&teil, ziel).await.inspect_err(|_| {
+                let _ = std::fs::remove_file(&teil);
+            })?;
+            tracing::debug!(ziel = %ziel.display(), bytes, "Datei heruntergeladen");
+            Ok(bytes)
+        }
+        Err(e) => {
+            let _ = tokio::fs::remove_file(&teil).await;
+            tracing::warn!(ziel = %ziel.display(), fehler = %e, "Download fehlgeschlagen");
+            Err(e)
+        }
+    }
+}
+
+async fn streamen(
+    client: &reqwest::Client,
+    url: &str,
+    erwartung: &Erwartung,
+    teil: &Path,
+) -> Result<u64, DownloadFehler> {
+    let mut antwort = client.get(url).send().await?;
+    match antwort.status() {
+        s if s.is_success() => {}
+        StatusCode::GONE => return Err(DownloadFehler::Abgelaufen),
+        StatusCode::NOT_FOUND => return Err(DownloadFehler::NichtGefunden),
+        StatusCode::FORBIDDEN => return Err(DownloadFehler::Abgelehnt),
+        s => return Err(DownloadFehler::Http(s.as_u16())),
+    }
+
+    let mut datei = File::create(teil).await?;
+    let mut hasher = Sha256::new();
+    let mut erhalten = 0u64;
+    while let Some(stueck) = antwort.chunk().await? {
+        erhalten += stueck.len() as u64;
+        // Nicht mehr schreiben als angekuendigt
+        if erhalten > erwartung.size_bytes {
+            return Err(DownloadFehler::Groesse {
+                erwartet: erwartung.size_bytes,
+                erhalten,
+            });
+        }
+        hasher.update(&stueck);
+        datei.write_all(&stueck).await?;
+    }
+    datei.flush().await?;
+    drop(datei);
+
+    if erhalten != erwartung.size_bytes {
+        return Err(DownloadFehler::Groesse {
+            erwartet: erwartung.size_bytes,
+            erhalten,
+        });
+    }
+    let hex: String = hasher
+        .finalize()
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect();
+    if !hex.eq_ignore_ascii_case(&erwartung.sha256) {
+        return Err(DownloadFehler::Pruefsumme);
+    }
+    Ok(erhalten)
+}
+
+/// `<ziel>.part` im selben Verzeichnis
+fn teildatei(ziel: &Path) -> PathBuf {
+    let mut name = ziel.file_name().unwrap_or_default().to_os_string();
+    name.push(".part");
+    ziel.with_file_name(name)
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::AsyncReadExt;
+    use tokio::net::TcpListener;
+
+    /// Minimaler HTTP-Server, der jede Anfrage gleich beantwortet
+    async fn server(status: &'static str, inhalt: &'static [u8]) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let adresse = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                let mut buf = [0u8; 1024];
+                let _ = socket.read(&mut buf).await;
+                let kopf = format!(
+                    "HTTP/1.1 {status}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                    inhalt.len()
+                );
+                let _ = socket.write_all(kopf.as_bytes()).await;
+                let _ = socket.write_all(inhalt).await;
+            }
+        });
+        format!("http://{adresse}/files/download/token")
+    }
+
+    fn erwartung(inhalt: &[u8]) -> Erwartung {
+        Erwartung {
+            size_bytes: inhalt.len() as u64,
+            sha256: Sha256::digest(inhalt)
+                .iter()
+                .map(|b| format!("{b:02x}"))
+                .collect(),
+        }
+    }
+
+    fn ziel(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("speakeasy-{}-{name}", uuid::Uuid::new_v4()))
+    }
+
+    #[tokio::test]
+    async fn download_mit_gueltiger_pruefsumme() {
+        let url = server("200 OK", b"Hallo Welt").await;
+        let ziel = ziel("ok.txt");
+        let bytes = herunterladen(
+            &reqwest::Client::new(),
+            &url,
+            &erwartung(b"Hallo Welt"),
+            &ziel,
+        )
+        .await
+        .unwrap();
+        assert_eq!(bytes, 10);
+        assert_eq!(std::fs::read(&ziel).unwrap(), b"Hallo Welt");
+        assert!(!teildatei(&ziel).exists());
+        std::fs::remove_file(ziel).unwrap();
+    }
+
+    #[tokio::test]
+    async fn falsche_pruefsumme_verwirft_teildatei() {
+        let url = server("200 OK", b"Hallo Welt").await;
+        let ziel = ziel("kaputt.txt");
+        let client = reqwest::Client::new();
+
+        let fehler = herunterladen(&client, &url, &erwartung(b"Hallo Welx"), &ziel)
+            .await
+            .unwrap_err();
+        assert!(matches!(fehler, DownloadFehler::Pruefsumme));
+        assert!(!ziel.exists() && !teildatei(&ziel).exists());
+
+        let mut kurz = erwartung(b"Hallo Welt");
+        kurz.size_bytes = 4;
+        let fehler = herunterladen(&client, &url, &kurz, &ziel)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            fehler,
+            DownloadFehler::Groesse { erwartet: 4, .. }
+        ));
+        assert!(!ziel.exists() && !teildatei(&ziel).exists());
+    }
+
+    #[tokio::test]
+    async fn http_status_wird_zugeordnet() {
+        let client = reqwest::Client::new();
+        let ziel = ziel("status.txt");
+        let erwartet = erwartung(b"");
+
+        let url = server("410 Gone", b"").await;
+        let fehler = herunterladen(&client, &url, &erwartet, &ziel)
+            .await
+            .unwrap_err();
+        assert!(matches!(fehler, DownloadFehler::Abgelaufen));
+
+        let url = server("404 Not Found", b"").await;
+        let fehler = herunterladen(&client, &url, &erwartet, &ziel)
+            .await
+            .unwrap_err();
+        assert!(matches!(fehler, DownloadFehler::NichtGefunden));
+
+        let url = server("500 Internal Server Error", b"").await;
+        let fehler = herunterladen(&client, &url, &erwartet, &ziel)
+            .await
+            .unwrap_err();
+        assert!(matches!(fehler, DownloadFehler::Http(500)));
+        assert!(!ziel.exists() && !teildatei(&ziel).exists());
+    }
+}
@@ -0,0 +1,162 @@
+//! Datei-Upload – Dateianhaenge ueber signierte HTTP-Links hochladen
+//!
+//! Der Server liefert per `FileUploadResponse` einen zeitlich begrenzten
+//! Link, an den der Inhalt per `PUT` geht. Erst die Antwort `200` bestaetigt,
+//! dass die Datei gespeichert und im Kanal gepostet wurde; alle anderen
+//! Statuscodes werden auf einen eigenen Fehler abgebildet.
+
+use reqwest::StatusCode;
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+
+/// Bestaetigung des Servers nach erfolgreichem Upload
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct Hochgeladen {
+    pub file_id: String,
+    /// ID der Chat-Nachricht, die auf die Datei verweist
+    pub message_id: String,
+    pub size_bytes: u64,
+    pub created_at: String,
+}
+
+/// Fehler beim Hochladen
+#[derive(Debug, thiserror::Error)]
+pub enum UploadFehler {
+    #[error("Upload-Link abgelaufen, bitte erneut hochladen")]
+    Abgelaufen,
+    #[error("Upload-Link wurde vom Server abgelehnt")]
+    Abgelehnt,
+    #[error("Upload nicht gefunden oder bereits abgeschlossen")]
+    NichtGefunden,
+    #[error("Server hat die Groesse abgelehnt: {0}")]
+    Groesse(String),
+    #[error("Datei zu gross: {0}")]
+    ZuGross(String),
+    #[error("Speicherkontingent erschoepft: {0}")]
+    Kontingent(String),
+    #[error("Pruefsumme vom Server abgelehnt, Datei beschaedigt uebertragen")]
+    Pruefsumme,
+    #[error("Server antwortete mit HTTP {0}")]
+    Http(u16),
+    #[error("Verbindung zum Datei-Server fehlgeschlagen: {0}")]
+    Netzwerk(#[from] reqwest::Error),
+}
+
+/// SHA-256 als Hex-String, wie ihn `FileUploadRequest.checksum` erwartet
+pub fn pruefsumme(daten: &[u8]) -> String {
+    Sha256::digest(daten)
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect()
+}
+
+/// Laedt `daten` per `PUT` an `url` hoch
+pub async fn hochladen(
+    client: &reqwest::Client,
+    url: &str,
+    daten: Vec<u8>,
+) -> Result<Hochgeladen, UploadFehler> {
+    let bytes = daten.len();
+    let antwort = client.put(url).body(daten).send().await?;
+    let status = antwort.status();
+    if status == StatusCode::OK {
+        let inhalt = antwort.bytes().await?;
+        let bestaetigung: Hochgeladen =
+            serde_json::from_slice(&inhalt).map_err(|_| UploadFehler::Http(status.as_u16()))?;
+        tracing::debug!(file_id = %bestaetigung.file_id, bytes, "Datei hochgeladen");
+        return Ok(bestaetigung);
+    }
+
+    // Der Server begruendet Ablehnungen im Klartext
+    let grund = antwort.text().await.unwrap_or_default();
+    let fehler = match status {
+        StatusCode::GONE => UploadFehler::Abgelaufen,
+        StatusCode::FORBIDDEN => UploadFehler::Abgelehnt,
+        StatusCode::NOT_FOUND => UploadFehler::NichtGefunden,
+        StatusCode::BAD_REQUEST => UploadFehler::Groesse(grund),
+        StatusCode::PAYLOAD_TOO_LARGE => UploadFehler::ZuGross(grund),
+        StatusCode::INSUFFICIENT_STORAGE => UploadFehler::Kontingent(grund),
+        StatusCode::UNPROCESSABLE_ENTITY => UploadFehler::Pruefsumme,
+        s => UploadFehler::Http(s.as_u16()),
+    };
+    tracing::warn!(bytes, fehler = %fehler, "Upload fehlgeschlagen");
+    Err(fehler)
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    /// Minimaler HTTP-Server, der jede Anfrage gleich beantwortet
+    async fn server(status: &'static str, inhalt: &'static str) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let adresse = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                let mut buf = [0u8; 1024];
+                let _ = socket.read(&mut buf).await;
+                let kopf = format!(
+                    "HTTP/1.1 {status}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                    inhalt.len()
+                );
+                let _ = socket.write_all(kopf.as_bytes()).await;
+                let _ = socket.write_all(inhalt.as_bytes()).await;
+            }
+        });
+        format!("http://{adresse}/files/upload/token")
+    }
+
+    #[test]
+    fn pruefsumme_als_hex() {
+        assert_eq!(
+            pruefsumme(b"Plan B"),
+            "915c4962dcba1db26a2fbc31678a5aff64fa6b2865424da24d494a2562ce6108"
+        );
+    }
+
+    #[tokio::test]
+    async fn erfolg_erst_nach_bestaetigung() {
+        let url = server(
+            "200 OK",
+            r#"{"file_id":"f1","message_id":"m1","size_bytes":6,"created_at":"2026-01-01T00:00:00Z"}"#,
+        )
+        .await;
+        let ergebnis = hochladen(&reqwest::Client::new(), &url, b"Plan B".to_vec())
+            .await
+            .unwrap();
+        assert_eq!(ergebnis.file_id, "f1");
+        assert_eq!(ergebnis.message_id, "m1");
+        assert_eq!(ergebnis.size_bytes, 6);
+    }
+
+    #[tokio::test]
+    async fn http_status_wird_zugeordnet() {
+        let client = reqwest::Client::new();
+
+        let url = server("410 Gone", "Upload-Link abgelaufen").await;
+        let fehler = hochladen(&client, &url, vec![1]).await.unwrap_err();
+        assert!(matches!(fehler, UploadFehler::Abgelaufen));
+
+        let url = server("507 Insufficient Storage", "Speicherkontingent erschoepft").await;
+        let fehler = hochladen(&client, &url, vec![1]).await.unwrap_err();
+        assert!(matches!(fehler, UploadFehler::Kontingent(ref g) if g.contains("erschoepft")));
+
+        let url = server("400 Bad Request", "Dateigroesse weicht ab").await;
+        let fehler = hochladen(&client, &url, vec![1]).await.unwrap_err();
+        assert!(matches!(fehler, UploadFehler::Groesse(_)));
+
+        let url = server("422 Unprocessable Entity", "").await;
+        let fehler = hochladen(&client, &url, vec![1]).await.unwrap_err();
+        assert!(matches!(fehler, UploadFehler::Pruefsumme));
+
+        let url = server("500 Internal Server Error", "").await;
+        let fehler = hochladen(&client, &url, vec![1]).await.unwrap_err();
+        assert!(matches!(fehler, UploadFehler::Http(500)));
+    }
+}
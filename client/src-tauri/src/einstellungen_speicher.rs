@@ -0,0 +1,290 @@
+//! Client-Einstellungen auf der Festplatte
+//!
+//! Audio-Einstellungen (Geraete, PTT-Taste, DSP, Lautstaerken) lagen bisher
+//! nur im `AppState` und waren nach jedem Neustart verloren. Der
+//! [`EinstellungsSpeicher`] haelt sie als JSON im Konfigurationsverzeichnis
+//! der App und schreibt ueber eine temporaere Datei plus `rename`, damit ein
+//! Absturz beim Speichern keine halbe Datei hinterlaesst.
+//!
+//! Beim Laden werden fehlende Felder mit Standardwerten aufgefuellt und
+//! unbekannte ignoriert, aeltere Dateien bleiben nach neuen Feldern also
+//! lesbar. Eine beschaedigte oder ungueltige Datei wird beiseitegelegt
+//! (`settings.json.defekt-<unix>`) und durch die Standardwerte ersetzt.
+
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tracing::{info, warn};
+
+use crate::benutzer_audio::jetzt_unix;
+use crate::commands::{default_audio_settings, AudioSettingsConfig};
+use crate::validation;
+
+/// Dateiname im Konfigurationsverzeichnis der App
+pub const DATEINAME: &str = "settings.json";
+
+/// Inhalt der Einstellungsdatei
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GespeicherteEinstellungen {
+    pub audio: AudioSettingsConfig,
+}
+
+impl Default for GespeicherteEinstellungen {
+    fn default() -> Self {
+        Self {
+            audio: default_audio_settings(),
+        }
+    }
+}
+
+/// Laedt und speichert [`GespeicherteEinstellungen`]
+#[derive(Debug, Default)]
+pub struct EinstellungsSpeicher {
+    /// `None` = nur im Speicher (kein Konfigurationsverzeichnis)
+    pfad: Option<PathBuf>,
+    stand: Mutex<GespeicherteEinstellungen>,
+}
+
+impl EinstellungsSpeicher {
+    /// Speicher fuer `verzeichnis/settings.json`, liest die Datei sofort
+    pub fn oeffnen(verzeichnis: &Path) -> Self {
+        let pfad = verzeichnis.join(DATEINAME);
+        let stand = laden(&pfad);
+        Self {
+            pfad: Some(pfad),
+            stand: Mutex::new(stand),
+        }
+    }
+
+    /// Speicher ohne Datei (Standardwerte, Aenderungen gehen beim Beenden verloren)
+    pub fn fluechtig() -> Self {
+        Self::default()
+    }
+
+    fn zustand(&self) -> MutexGuard<'_, GespeicherteEinstellungen> {
+        self.stand.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Aktuell gespeicherte Einstellungen
+    pub fn einstellungen(&self) -> GespeicherteEinstellungen {
+        self.zustand().clone()
+    }
+
+    /// Uebernimmt neue Audio-Einstellungen und schreibt die Datei
+    pub fn audio_speichern(&self, audio: AudioSettingsConfig) -> io::Result<()> {
+        let mut stand = self.zustand();
+        stand.audio = audio;
+        match &self.pfad {
+            Some(pfad) => schreiben(pfad, &stand),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Liest die Datei; fehlt sie, gelten die Standardwerte
+fn laden(pfad: &Path) -> GespeicherteEinstellungen {
+    let inhalt = match std::fs::read(pfad) {
+        Ok(inhalt) => inhalt,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {
+            info!("Keine gespeicherten Einstellungen, verwende Standardwerte");
+            return GespeicherteEinstellungen::default();
+        }
+        Err(e) => {
+            warn!("Einstellungen {} nicht lesbar: {}", pfad.display(), e);
+            return GespeicherteEinstellungen::default();
+        }
+    };
+    match auswerten(&inhalt) {
+        Ok(einstellungen) => {
+            info!("Einstellungen aus {} geladen", pfad.display());
+            einstellungen
+        }
+        Err(grund) => {
+            warn!(
+                "Einstellungen {} beschaedigt ({}), ersetze durch Standardwerte",
+                pfad.display(),
+                grund
+            );
+            ersetzen(pfad);
+            GespeicherteEinstellungen::default()
+        }
+    }
+}
+
+/// Dekodiert die Datei, ergaenzt fehlende Felder und prueft die Werte
+fn auswerten(inhalt: &[u8]) -> Result<GespeicherteEinstellungen, String> {
+    let datei: Value = serde_json::from_slice(inhalt).map_err(|e| e.to_string())?;
+    let mut wert =
+        serde_json::to_value(GespeicherteEinstellungen::default()).map_err(|e| e.to_string())?;
+    auffuellen(&mut wert, datei);
+    let einstellungen: GespeicherteEinstellungen =
+        serde_json::from_value(wert).map_err(|e| e.to_string())?;
+    validation::audio_einstellungen(&einstellungen.audio).map_err(|e| e.to_string())?;
+    validation::ptt(
+        &einstellungen.audio.voice_mode,
+        einstellungen.audio.ptt_key.as_deref(),
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(einstellungen)
+}
+
+/// Legt Werte aus `datei` ueber die Standardwerte; Objekte werden rekursiv
+/// zusammengefuehrt, Felder, die es nicht (mehr) gibt, fallen spaeter beim
+/// Dekodieren weg
+fn auffuellen(standard: &mut Value, datei: Value) {
+    match (standard, datei) {
+        (Value::Object(standard), Value::Object(datei)) => {
+            for (schluessel, wert) in datei {
+                match standard.get_mut(&schluessel) {
+                    Some(ziel) => auffuellen(ziel, wert),
+                    None => {
+                        standard.insert(schluessel, wert);
+                    }
+                }
+            }
+        }
+        (standard, datei) => *standard = datei,
+    }
+}
+
+/// Legt eine beschaedigte Datei beiseite und schreibt die Standardwerte
+fn ersetzen(pfad: &Path) {
+    let mut sicherung = pfad.as_os_str().to_owned();
+    sicherung.push(format!(".defekt-{}", jetzt_unix()));
+    if let Err(e) = std::fs::rename(pfad, &sicherung) {
+        warn!("Beschaedigte Einstellungen nicht gesichert: {}", e);
+    }
+    if let Err(e) = schreiben(pfad, &GespeicherteEinstellungen::default()) {
+        warn!("Standard-Einstellungen nicht geschrieben: {}", e);
+    }
+}
+
+/// Ersetzt die Datei atomar (legt das Verzeichnis bei Bedarf an)
+fn schreiben(pfad: &Path, einstellungen: &GespeicherteEinstellungen) -> io::Result<()> {
+    if let Some(verzeichnis) = pfad.parent() {
+        std::fs::create_dir_all(verzeichnis)?;
+    }
+    let json = serde_json::to_vec_pretty(einstellungen)?;
+    let mut temp = pfad.as_os_str().to_owned();
+    temp.push(".tmp");
+    std::fs::write(&temp, json)?;
+    std::fs::rename(&temp, pfad)
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn verzeichnis() -> PathBuf {
+        std::env::temp_dir().join(format!("speakeasy-einstellungen-{}", uuid::Uuid::new_v4()))
+    }
+
+    #[test]
+    fn speichern_und_wieder_laden() {
+        let dir = verzeichnis();
+        let speicher = EinstellungsSpeicher::oeffnen(&dir);
+        let mut audio = speicher.einstellungen().audio;
+        audio.input_device_id = Some("Headset".into());
+        audio.voice_mode = "ptt_hold".into();
+        audio.ptt_key = Some("F13".into());
+        audio.dsp.agc.max_gain = 12.0;
+        audio.output_volume = 0.7;
+        speicher.audio_speichern(audio).unwrap();
+
+        let geladen = EinstellungsSpeicher::oeffnen(&dir).einstellungen().audio;
+        assert_eq!(geladen.input_device_id.as_deref(), Some("Headset"));
+        assert_eq!(geladen.ptt_key.as_deref(), Some("F13"));
+        assert_eq!(geladen.dsp.agc.max_gain, 12.0);
+        assert_eq!(geladen.output_volume, 0.7);
+        assert!(!dir.join("settings.json.tmp").exists());
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn fehlende_felder_werden_aufgefuellt_unbekannte_ignoriert() {
+        let alt = br#"{
+            "audio": {
+                "outputDeviceId": "Lautsprecher",
+                "inputVolume": 0.5,
+                "dsp": { "agc": { "enabled": false } },
+                "spaeterEntfernt": true
+            },
+            "ui": { "theme": "dunkel" }
+        }"#;
+        let einstellungen = auswerten(alt).unwrap();
+        let standard = default_audio_settings();
+        assert_eq!(
+            einstellungen.audio.output_device_id.as_deref(),
+            Some("Lautsprecher")
+        );
+        assert_eq!(einstellungen.audio.input_volume, 0.5);
+        assert!(!einstellungen.audio.dsp.agc.enabled);
+        assert_eq!(
+            einstellungen.audio.dsp.agc.target_level,
+            standard.dsp.agc.target_level
+        );
+        assert_eq!(einstellungen.audio.voice_mode, standard.voice_mode);
+        assert_eq!(
+            einstellungen.audio.codec.dtx_keepalive_ms,
+            standard.codec.dtx_keepalive_ms
+        );
+    }
+
+    #[test]
+    fn beschaedigte_datei_wird_gesichert_und_ersetzt() {
+        let dir = verzeichnis();
+        std::fs::create_dir_all(&dir).unwrap();
+        let pfad = dir.join(DATEINAME);
+        for kaputt in [
+            &b"{\"audio\": {"[..],
+            &br#"{"audio": {"inputVolume": "laut"}}"#[..],
+        ] {
+            std::fs::write(&pfad, kaputt).unwrap();
+
+            let speicher = EinstellungsSpeicher::oeffnen(&dir);
+            assert_eq!(
+                speicher.einstellungen().audio.voice_mode,
+                default_audio_settings().voice_mode
+            );
+            // Die Datei ist wieder lesbar, das Original liegt daneben
+            assert!(auswerten(&std::fs::read(&pfad).unwrap()).is_ok());
+            let sicherungen: Vec<_> = std::fs::read_dir(&dir)
+                .unwrap()
+                .filter_map(|e| e.ok())
+                .filter(|e| e.file_name().to_string_lossy().contains(".defekt-"))
+                .collect();
+            assert_eq!(sicherungen.len(), 1);
+            assert_eq!(std::fs::read(sicherungen[0].path()).unwrap(), kaputt);
+            std::fs::remove_file(sicherungen[0].path()).unwrap();
+        }
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn ungueltige_werte_gelten_als_beschaedigt() {
+        assert!(auswerten(br#"{"audio": {"voiceMode": "telepathie"}}"#).is_err());
+        assert!(auswerten(br#"{"audio": {"outputVolume": 500.0}}"#).is_err());
+        assert!(auswerten(b"[]").is_err());
+    }
+
+    #[test]
+    fn ohne_datei_gelten_standardwerte() {
+        let dir = verzeichnis();
+        let speicher = EinstellungsSpeicher::oeffnen(&dir);
+        assert_eq!(
+            speicher.einstellungen().audio.preset,
+            default_audio_settings().preset
+        );
+        assert!(!dir.exists());
+    }
+}
@@ -0,0 +1,242 @@
+//! Entwuerfe des Eingabefelds pro Kanal
+//!
+//! Lange Nachrichten sollen Kanalwechsel, ein Neuladen der Webview und
+//! Neustarts ueberstehen. Wie die Lautstaerken pro Benutzer speichert das
+//! Frontend die Tabelle mit den uebrigen Client-Einstellungen und reicht sie
+//! beim Start herein; jede Aenderung liefert die aktualisierte Tabelle zum
+//! Speichern zurueck.
+//!
+//! Ein Entwurf ist hoechstens [`MAX_ENTWURF_ZEICHEN`] lang, alle zusammen
+//! belegen hoechstens [`MAX_ENTWUERFE_BYTES`]. Wird es mehr, fallen die am
+//! laengsten nicht mehr bearbeiteten Entwuerfe weg. Bestaetigt der Server
+//! eine Nachricht, verwirft `send_message` den Entwurf ihres Kanals und
+//! meldet die Tabelle mit [`ENTWUERFE_EREIGNIS`].
+
+use std::collections::HashMap;
+use std::sync::{Mutex, MutexGuard};
+
+use serde::{Deserialize, Serialize};
+use speakeasy_core::types::ChannelId;
+use speakeasy_protocol::control::MAX_NACHRICHT_ZEICHEN;
+
+/// Hoechstlaenge eines Entwurfs in Zeichen
+///
+/// Grosszuegiger als eine Nachricht, damit beim Kuerzen nichts verloren geht.
+pub const MAX_ENTWURF_ZEICHEN: usize = 4 * MAX_NACHRICHT_ZEICHEN;
+
+/// Gesamtgroesse aller Entwuerfe in Bytes (UTF-8)
+pub const MAX_ENTWUERFE_BYTES: usize = 512 * 1024;
+
+/// Tauri-Event nach automatisch verworfenen Entwuerfen (Nutzdaten: [`EntwurfsEinstellungen`])
+pub const ENTWUERFE_EREIGNIS: &str = "drafts_changed";
+
+/// Gespeicherter Entwurf eines Kanals
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Entwurf {
+    pub text: String,
+    /// Zuletzt bearbeitet (Unix-Sekunden)
+    pub updated_at: u64,
+}
+
+/// Alle gespeicherten Entwuerfe
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EntwurfsEinstellungen {
+    #[serde(default)]
+    pub drafts: HashMap<ChannelId, Entwurf>,
+}
+
+impl EntwurfsEinstellungen {
+    /// Belegte Bytes aller Entwuerfe
+    pub fn groesse(&self) -> usize {
+        self.drafts.values().map(|e| e.text.len()).sum()
+    }
+
+    /// Verwirft die aeltesten Entwuerfe, bis alle zusammen unter die Grenze passen
+    ///
+    /// Gibt die Anzahl verworfener Entwuerfe zurueck.
+    pub fn kuerzen(&mut self) -> usize {
+        let mut groesse = self.groesse();
+        if groesse <= MAX_ENTWUERFE_BYTES {
+            return 0;
+        }
+        let mut nach_alter: Vec<(u64, ChannelId)> = self
+            .drafts
+            .iter()
+            .map(|(kanal, entwurf)| (entwurf.updated_at, *kanal))
+            .collect();
+        nach_alter.sort_by_key(|(zeit, kanal)| (*zeit, kanal.0));
+
+        let mut verworfen = 0;
+        for (_, kanal) in nach_alter {
+            if groesse <= MAX_ENTWUERFE_BYTES {
+                break;
+            }
+            if let Some(entwurf) = self.drafts.remove(&kanal) {
+                groesse -= entwurf.text.len();
+                verworfen += 1;
+            }
+        }
+        verworfen
+    }
+}
+
+/// Entwuerfe im `AppState`, geteilt zwischen Befehlen
+#[derive(Debug, Default)]
+pub struct Entwuerfe {
+    einstellungen: Mutex<EntwurfsEinstellungen>,
+}
+
+impl Entwuerfe {
+    pub fn neu() -> Self {
+        Self::default()
+    }
+
+    fn zustand(&self) -> MutexGuard<'_, EntwurfsEinstellungen> {
+        self.einstellungen.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Kopie aller Entwuerfe (zum Speichern bzw. Exportieren)
+    pub fn einstellungen(&self) -> EntwurfsEinstellungen {
+        self.zustand().clone()
+    }
+
+    /// Ersetzt alle Entwuerfe (z.B. beim Start oder Import) und kuerzt sie
+    ///
+    /// Gibt die Anzahl verworfener Entwuerfe zurueck.
+    pub fn laden(&self, mut einstellungen: EntwurfsEinstellungen) -> usize {
+        let verworfen = einstellungen.kuerzen();
+        *self.zustand() = einstellungen;
+        verworfen
+    }
+
+    /// Speichert den Entwurf eines Kanals (leerer Text entfernt ihn)
+    ///
+    /// Gibt die Anzahl dafuer verworfener aelterer Entwuerfe zurueck.
+    pub fn speichern(&self, kanal: ChannelId, text: String, jetzt: u64) -> usize {
+        let mut zustand = self.zustand();
+        if text.is_empty() {
+            zustand.drafts.remove(&kanal);
+            return 0;
+        }
+        zustand.drafts.insert(
+            kanal,
+            Entwurf {
+                text,
+                updated_at: jetzt,
+            },
+        );
+        zustand.kuerzen()
+    }
+
+    /// Entwurf eines Kanals
+    pub fn lesen(&self, kanal: &ChannelId) -> Option<String> {
+        self.zustand().drafts.get(kanal).map(|e| e.text.clone())
+    }
+
+    /// Verwirft den Entwurf eines Kanals; `true` wenn es einen gab
+    pub fn verwerfen(&self, kanal: &ChannelId) -> bool {
+        self.zustand().drafts.remove(kanal).is_some()
+    }
+
+    /// Der Server hat eine Nachricht in `kanal` bestaetigt
+    ///
+    /// Verwirft den Entwurf und liefert die Tabelle zum Speichern (`None` =
+    /// es gab keinen Entwurf, nichts zu melden).
+    pub fn bestaetigt(&self, kanal: &ChannelId) -> Option<EntwurfsEinstellungen> {
+        let mut zustand = self.zustand();
+        zustand.drafts.remove(kanal)?;
+        Some(zustand.clone())
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uuid::Uuid;
+
+    fn kanal(n: u128) -> ChannelId {
+        ChannelId(Uuid::from_u128(n))
+    }
+
+    /// Entwurf, der ein Viertel der Gesamtgrenze belegt
+    fn viertel() -> String {
+        "x".repeat(MAX_ENTWUERFE_BYTES / 4)
+    }
+
+    #[test]
+    fn speichern_lesen_und_leeren() {
+        let entwuerfe = Entwuerfe::neu();
+        entwuerfe.speichern(kanal(1), "Hallo\nzweite Zeile".into(), 10);
+        assert_eq!(
+            entwuerfe.lesen(&kanal(1)).as_deref(),
+            Some("Hallo\nzweite Zeile")
+        );
+        assert_eq!(entwuerfe.lesen(&kanal(2)), None);
+
+        entwuerfe.speichern(kanal(1), String::new(), 11);
+        assert_eq!(entwuerfe.lesen(&kanal(1)), None);
+    }
+
+    #[test]
+    fn aelteste_entwuerfe_werden_verdraengt() {
+        let entwuerfe = Entwuerfe::neu();
+        // Kanal 2 ist am aeltesten, obwohl er nicht zuerst gespeichert wurde
+        entwuerfe.speichern(kanal(1), viertel(), 20);
+        entwuerfe.speichern(kanal(2), viertel(), 10);
+        entwuerfe.speichern(kanal(3), viertel(), 30);
+        assert_eq!(entwuerfe.speichern(kanal(4), viertel(), 40), 0);
+
+        assert_eq!(entwuerfe.speichern(kanal(5), viertel(), 50), 1);
+        assert_eq!(entwuerfe.lesen(&kanal(2)), None);
+        assert!(entwuerfe.lesen(&kanal(1)).is_some());
+        assert!(entwuerfe.einstellungen().groesse() <= MAX_ENTWUERFE_BYTES);
+
+        // Bearbeiten macht einen Entwurf wieder jung
+        entwuerfe.speichern(kanal(1), viertel(), 60);
+        assert_eq!(entwuerfe.speichern(kanal(6), viertel(), 70), 1);
+        assert_eq!(entwuerfe.lesen(&kanal(3)), None);
+        assert!(entwuerfe.lesen(&kanal(1)).is_some());
+    }
+
+    #[test]
+    fn laden_kuerzt_zu_grosse_tabellen() {
+        let mut einstellungen = EntwurfsEinstellungen::default();
+        for n in 0..6 {
+            einstellungen.drafts.insert(
+                kanal(n),
+                Entwurf {
+                    text: viertel(),
+                    updated_at: n as u64,
+                },
+            );
+        }
+        let entwuerfe = Entwuerfe::neu();
+        assert_eq!(entwuerfe.laden(einstellungen), 2);
+        assert_eq!(entwuerfe.lesen(&kanal(0)), None);
+        assert_eq!(entwuerfe.lesen(&kanal(1)), None);
+        assert!(entwuerfe.lesen(&kanal(2)).is_some());
+    }
+
+    #[test]
+    fn bestaetigte_nachricht_verwirft_nur_ihren_entwurf() {
+        let entwuerfe = Entwuerfe::neu();
+        entwuerfe.speichern(kanal(1), "gleich gesendet".into(), 1);
+        entwuerfe.speichern(kanal(2), "bleibt".into(), 2);
+
+        let tabelle = entwuerfe.bestaetigt(&kanal(1)).unwrap();
+        assert!(!tabelle.drafts.contains_key(&kanal(1)));
+        assert_eq!(tabelle.drafts[&kanal(2)].text, "bleibt");
+        assert_eq!(entwuerfe.lesen(&kanal(1)), None);
+
+        // Zweite Bestaetigung (oder Kanal ohne Entwurf): nichts zu melden
+        assert_eq!(entwuerfe.bestaetigt(&kanal(1)), None);
+        assert!(entwuerfe.verwerfen(&kanal(2)));
+        assert!(!entwuerfe.verwerfen(&kanal(2)));
+    }
+}
@@ -0,0 +1,626 @@
+//! Event-Sounds – akustische Hinweise fuer Kanal- und Client-Ereignisse
+//!
+//! Spielt kurze Sounds fuer Join, Leave, Poke, Mention und Mute-Toggle ueber
+//! die Effekt-Quelle des Playback-Mixers ab. Die Lautstaerke ist unabhaengig
+//! von der Sprach-Lautstaerke, die Sprache wird waehrend eines Sounds
+//! hoechstens um den konfigurierten Ducking-Anteil abgesenkt.
+//!
+//! Join/Leave folgen den Presence-Ereignissen des Servers
+//! (`ClientJoinedChannel`/`ClientLeftChannel`), sobald die Ereignis-Schleife
+//! sie abholt; beruecksichtigt werden nur andere Clients im eigenen Kanal.
+//!
+//! Eigene Sound-Packs sind Verzeichnisse mit `<ereignis>.wav` oder
+//! `<ereignis>.ogg` (z.B. `join.ogg`). Fehlende Dateien fallen auf den
+//! eingebauten Sound zurueck; ungueltige Dateien lassen das Laden scheitern.
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+/// Abtastrate des Playback-Mixers
+const SAMPLE_RATE: u32 = 48000;
+
+/// Maximale Laenge eines Sounds in Sekunden
+pub const MAX_SOUND_DAUER_SEKUNDEN: u32 = 3;
+
+/// Maximale Dateigroesse eines Sounds (1 MiB)
+pub const MAX_SOUND_DATEIGROESSE: u64 = 1024 * 1024;
+
+/// Nach dem eigenen Kanal-Beitritt werden so lange keine Sounds gespielt
+/// (verhindert Sounds fuer Ereignisse, die den eigenen Beitritt begleiten)
+pub const KARENZZEIT_NACH_BEITRITT: Duration = Duration::from_secs(2);
+
+// ---------------------------------------------------------------------------
+// Ereignisse und Einstellungen
+// ---------------------------------------------------------------------------
+
+/// Ereignisse, fuer die ein Sound abgespielt werden kann
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SoundEreignis {
+    Join,
+    Leave,
+    Poke,
+    Mention,
+    MuteToggle,
+}
+
+impl SoundEreignis {
+    pub const ALLE: [SoundEreignis; 5] = [
+        SoundEreignis::Join,
+        SoundEreignis::Leave,
+        SoundEreignis::Poke,
+        SoundEreignis::Mention,
+        SoundEreignis::MuteToggle,
+    ];
+
+    /// Dateiname (ohne Endung) im Sound-Pack
+    pub fn dateiname(self) -> &'static str {
+        match self {
+            SoundEreignis::Join => "join",
+            SoundEreignis::Leave => "leave",
+            SoundEreignis::Poke => "poke",
+            SoundEreignis::Mention => "mention",
+            SoundEreignis::MuteToggle => "mute_toggle",
+        }
+    }
+}
+
+/// Event-Sound-Einstellungen (Teil der Benachrichtigungs-Einstellungen)
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct EventSoundSettings {
+    /// Lautstaerke der Event-Sounds in Prozent (0 - 100)
+    pub volume: f32,
+    /// Maximale Absenkung der Sprache waehrend eines Sounds in Prozent (0 - 100)
+    pub max_ducking: f32,
+    pub join: bool,
+    pub leave: bool,
+    pub poke: bool,
+    pub mention: bool,
+    pub mute_toggle: bool,
+    /// Verzeichnis eines eigenen Sound-Packs (None = eingebaute Sounds)
+    pub sound_pack_dir: Option<String>,
+}
+
+impl Default for EventSoundSettings {
+    fn default() -> Self {
+        Self {
+            volume: 50.0,
+            max_ducking: 20.0,
+            join: true,
+            leave: true,
+            poke: true,
+            mention: true,
+            mute_toggle: true,
+            sound_pack_dir: None,
+        }
+    }
+}
+
+impl EventSoundSettings {
+    /// Ist der Sound fuer dieses Ereignis aktiviert?
+    pub fn aktiviert(&self, ereignis: SoundEreignis) -> bool {
+        match ereignis {
+            SoundEreignis::Join => self.join,
+            SoundEreignis::Leave => self.leave,
+            SoundEreignis::Poke => self.poke,
+            SoundEreignis::Mention => self.mention,
+            SoundEreignis::MuteToggle => self.mute_toggle,
+        }
+    }
+
+    /// Event-Lautstaerke als Faktor (0.0 - 1.0)
+    pub fn lautstaerke_faktor(&self) -> f32 {
+        (self.volume / 100.0).clamp(0.0, 1.0)
+    }
+
+    /// Ducking-Anteil als Faktor (0.0 - 1.0)
+    pub fn ducking_faktor(&self) -> f32 {
+        (self.max_ducking / 100.0).clamp(0.0, 1.0)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Sound-Pack
+// ---------------------------------------------------------------------------
+
+/// Satz von Sounds (Mono, 48 kHz) je Ereignis
+#[derive(Debug, Clone)]
+pub struct SoundPack {
+    sounds: HashMap<SoundEreignis, Arc<[f32]>>,
+}
+
+impl SoundPack {
+    /// Eingebauter Sound-Satz (synthetisch erzeugte Toene)
+    pub fn eingebaut() -> Self {
+        let sounds = SoundEreignis::ALLE
+            .into_iter()
+            .map(|e| (e, Arc::from(eingebauter_sound(e))))
+            .collect();
+        Self { sounds }
+    }
+
+    /// Laedt ein Sound-Pack aus einem Verzeichnis
+    ///
+    /// Jede gefundene Datei wird auf Groesse, Format und Dauer geprueft.
+    /// Ereignisse ohne Datei nutzen den eingebauten Sound.
+    pub fn laden(verzeichnis: &Path) -> Result<Self, String> {
+        if !verzeichnis.is_dir() {
+            return Err(format!(
+                "Sound-Pack-Verzeichnis nicht gefunden: {}",
+                verzeichnis.display()
+            ));
+        }
+
+        let mut pack = Self::eingebaut();
+        for ereignis in SoundEreignis::ALLE {
+            for endung in ["wav", "ogg"] {
+                let pfad = verzeichnis.join(format!("{}.{}", ereignis.dateiname(), endung));
+                if !pfad.is_file() {
+                    continue;
+                }
+                let samples =
+                    sound_datei_laden(&pfad).map_err(|e| format!("{}: {}", pfad.display(), e))?;
+                pack.sounds.insert(ereignis, Arc::from(samples));
+                break;
+            }
+        }
+        Ok(pack)
+    }
+
+    /// Samples fuer ein Ereignis
+    pub fn sound(&self, ereignis: SoundEreignis) -> Option<&[f32]> {
+        self.sounds.get(&ereignis).map(|s| s.as_ref())
+    }
+}
+
+/// Laedt und validiert eine einzelne Sound-Datei
+fn sound_datei_laden(pfad: &Path) -> Result<Vec<f32>, String> {
+    let groesse = std::fs::metadata(pfad).map_err(|e| e.to_string())?.len();
+    if groesse > MAX_SOUND_DATEIGROESSE {
+        return Err(format!(
+            "Datei zu gross ({} Bytes, maximal {})",
+            groesse, MAX_SOUND_DATEIGROESSE
+        ));
+    }
+
+    let (samples, rate) = match pfad.extension().and_then(|e| e.to_str()) {
+        Some("wav") => wav_dekodieren(pfad)?,
+        Some("ogg") => ogg_dekodieren(pfad)?,
+        _ => return Err("Nicht unterstuetztes Format".to_string()),
+    };
+
+    if samples.is_empty() {
+        return Err("Datei enthaelt keine Samples".to_string());
+    }
+    pruefe_dauer(samples.len(), rate)?;
+
+    Ok(resampeln(&samples, rate, SAMPLE_RATE))
+}
+
+/// Prueft die Dauer einer Mono-Sample-Folge
+fn pruefe_dauer(anzahl: usize, rate: u32) -> Result<(), String> {
+    if rate == 0 {
+        return Err("Ungueltige Abtastrate".to_string());
+    }
+    if anzahl as u64 > u64::from(MAX_SOUND_DAUER_SEKUNDEN) * u64::from(rate) {
+        return Err(format!(
+            "Sound zu lang ({:.1} s, maximal {} s)",
+            anzahl as f32 / rate as f32,
+            MAX_SOUND_DAUER_SEKUNDEN
+        ));
+    }
+    Ok(())
+}
+
+/// Dekodiert eine WAV-Datei zu Mono-Samples
+fn wav_dekodieren(pfad: &Path) -> Result<(Vec<f32>, u32), String> {
+    let mut reader = hound::WavReader::open(pfad).map_err(|e| e.to_string())?;
+    let spec = reader.spec();
+    let kanaele = spec.channels.max(1) as usize;
+
+    // Dauer vor dem Dekodieren pruefen (Header reicht aus)
+    pruefe_dauer(reader.duration() as usize, spec.sample_rate)?;
+
+    let interleaved: Vec<f32> = match spec.sample_format {
+        hound::SampleFormat::Float => reader
+            .samples::<f32>()
+            .collect::<Result<_, _>>()
+            .map_err(|e| e.to_string())?,
+        hound::SampleFormat::Int => {
+            let skala = (1i64 << (spec.bits_per_sample.saturating_sub(1))) as f32;
+            reader
+                .samples::<i32>()
+                .map(|s| s.map(|v| v as f32 / skala))
+                .collect::<Result<_, _>>()
+                .map_err(|e| e.to_string())?
+        }
+    };
+
+    Ok((zu_mono(&interleaved, kanaele), spec.sample_rate))
+}
+
+/// Dekodiert eine Ogg-Vorbis-Datei zu Mono-Samples
+fn ogg_dekodieren(pfad: &Path) -> Result<(Vec<f32>, u32), String> {
+    let datei = std::fs::File::open(pfad).map_err(|e| e.to_string())?;
+    let mut reader = lewton::inside_ogg::OggStreamReader::new(datei).map_err(|e| e.to_string())?;
+    let rate = reader.ident_hdr.audio_sample_rate;
+    let kanaele = reader.ident_hdr.audio_channels.max(1) as usize;
+    let max_samples = MAX_SOUND_DAUER_SEKUNDEN as usize * rate as usize * kanaele;
+
+    let mut interleaved: Vec<f32> = Vec::new();
+    while let Some(paket) = reader.read_dec_packet_itl().map_err(|e| e.to_string())? {
+        interleaved.extend(paket.iter().map(|&s| s as f32 / i16::MAX as f32));
+        // Abbrechen statt ueberlange Dateien komplett zu dekodieren
+        if interleaved.len() > max_samples {
+            break;
+        }
+    }
+
+    Ok((zu_mono(&interleaved, kanaele), rate))
+}
+
+/// Mischt interleaved Mehrkanal-Samples auf Mono herunter
+fn zu_mono(interleaved: &[f32], kanaele: usize) -> Vec<f32> {
+    if kanaele <= 1 {
+        return interleaved.to_vec();
+    }
+    interleaved
+        .chunks_exact(kanaele)
+        .map(|frame| frame.iter().sum::<f32>() / kanaele as f32)
+        .collect()
+}
+
+/// Lineares Resampling auf die Ziel-Abtastrate
+fn resampeln(samples: &[f32], von: u32, nach: u32) -> Vec<f32> {
+    if von == nach || samples.len() < 2 {
+        return samples.to_vec();
+    }
+    let laenge = (samples.len() as u64 * u64::from(nach) / u64::from(von)) as usize;
+    let schritt = von as f64 / nach as f64;
+    (0..laenge)
+        .map(|i| {
+            let pos = i as f64 * schritt;
+            let idx = pos as usize;
+            let frac = (pos - idx as f64) as f32;
+            let a = samples[idx.min(samples.len() - 1)];
+            let b = samples[(idx + 1).min(samples.len() - 1)];
+            a + (b - a) * frac
+        })
+        .collect()
+}
+
+/// Erzeugt den eingebauten Sound fuer ein Ereignis
+fn eingebauter_sound(ereignis: SoundEreignis) -> Vec<f32> {
+    // (Frequenz in Hz, Dauer in ms) je Ton
+    let toene: &[(f32, u32)] = match ereignis {
+        SoundEreignis::Join => &[(660.0, 80), (880.0, 120)],
+        SoundEreignis::Leave => &[(880.0, 80), (660.0, 120)],
+        SoundEreignis::Poke => &[
+            (1000.0, 60),
+            (0.0, 40),
+            (1000.0, 60),
+            (0.0, 40),
+            (1000.0, 60),
+        ],
+        SoundEreignis::Mention => &[(1320.0, 150)],
+        SoundEreignis::MuteToggle => &[(440.0, 60)],
+    };
+    toene.iter().flat_map(|&(f, ms)| ton(f, ms)).collect()
+}
+
+/// Sinuston mit kurzer Ein-/Ausblendung (vermeidet Knackser)
+fn ton(frequenz: f32, dauer_ms: u32) -> Vec<f32> {
+    let anzahl = (SAMPLE_RATE * dauer_ms / 1000) as usize;
+    if frequenz <= 0.0 {
+        return vec![0.0; anzahl];
+    }
+    let rampe = (SAMPLE_RATE as usize / 200).min(anzahl / 2).max(1); // 5 ms
+    (0..anzahl)
+        .map(|i| {
+            let t = i as f32 / SAMPLE_RATE as f32;
+            let huelle = (i.min(anzahl - 1 - i) as f32 / rampe as f32).min(1.0);
+            0.3 * huelle * (2.0 * std::f32::consts::PI * frequenz * t).sin()
+        })
+        .collect()
+}
+
+// ---------------------------------------------------------------------------
+// Presence
+// ---------------------------------------------------------------------------
+
+/// Presence-Ereignis des Servers (Kanal-Beitritt oder -Abgang eines Clients)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PraesenzEreignis {
+    ClientJoinedChannel { user_id: String, channel_id: String },
+    ClientLeftChannel { user_id: String, channel_id: String },
+}
+
+impl PraesenzEreignis {
+    pub fn sound(&self) -> SoundEreignis {
+        match self {
+            PraesenzEreignis::ClientJoinedChannel { .. } => SoundEreignis::Join,
+            PraesenzEreignis::ClientLeftChannel { .. } => SoundEreignis::Leave,
+        }
+    }
+
+    fn beteiligte(&self) -> (&str, &str) {
+        match self {
+            PraesenzEreignis::ClientJoinedChannel {
+                user_id,
+                channel_id,
+            }
+            | PraesenzEreignis::ClientLeftChannel {
+                user_id,
+                channel_id,
+            } => (user_id, channel_id),
+        }
+    }
+}
+
+/// Eigener Kanal und eigene User-ID waehrend einer Kanal-Mitgliedschaft
+#[derive(Debug, Clone)]
+struct EigenerKanal {
+    kanal: String,
+    selbst: String,
+}
+
+// ---------------------------------------------------------------------------
+// EventSounds
+// ---------------------------------------------------------------------------
+
+/// Zustand der Event-Sounds (Einstellungen, geladenes Pack, Presence)
+#[derive(Debug)]
+pub struct EventSounds {
+    einstellungen: EventSoundSettings,
+    pack: SoundPack,
+    beigetreten_um: Option<Instant>,
+    eigener_kanal: Option<EigenerKanal>,
+}
+
+impl Default for EventSounds {
+    fn default() -> Self {
+        Self {
+            einstellungen: EventSoundSettings::default(),
+            pack: SoundPack::eingebaut(),
+            beigetreten_um: None,
+            eigener_kanal: None,
+        }
+    }
+}
+
+impl EventSounds {
+    pub fn einstellungen(&self) -> &EventSoundSettings {
+        &self.einstellungen
+    }
+
+    /// Uebernimmt neue Einstellungen
+    ///
+    /// Ein eigenes Sound-Pack wird vorher vollstaendig geladen und geprueft;
+    /// bei einem Fehler bleiben die bisherigen Einstellungen aktiv.
+    pub fn einstellungen_setzen(
+        &mut self,
+        einstellungen: EventSoundSettings,
+    ) -> Result<(), String> {
+        let pack = match einstellungen.sound_pack_dir.as_deref() {
+            Some(dir) if !dir.trim().is_empty() => SoundPack::laden(Path::new(dir))?,
+            _ => SoundPack::eingebaut(),
+        };
+        self.pack = pack;
+        self.einstellungen = einstellungen;
+        Ok(())
+    }
+
+    /// Merkt sich den eigenen Kanal und den Zeitpunkt des Beitritts
+    pub fn kanal_beigetreten(&mut self, kanal: String, selbst: String, jetzt: Instant) {
+        self.beigetreten_um = Some(jetzt);
+        self.eigener_kanal = Some(EigenerKanal { kanal, selbst });
+    }
+
+    /// Setzt den Zustand nach Verlassen des Kanals zurueck
+    pub fn kanal_verlassen(&mut self) {
+        self.beigetreten_um = None;
+        self.eigener_kanal = None;
+    }
+
+    /// Sound fuer ein Presence-Ereignis des Servers
+    ///
+    /// `None` fuer Ereignisse in fremden Kanaelen, fuer den eigenen Client
+    /// und solange man in keinem Kanal ist.
+    pub fn praesenz_sound(&self, ereignis: &PraesenzEreignis) -> Option<SoundEreignis> {
+        let eigen = self.eigener_kanal.as_ref()?;
+        let (user_id, channel_id) = ereignis.beteiligte();
+        (channel_id == eigen.kanal && user_id != eigen.selbst).then(|| ereignis.sound())
+    }
+
+    /// Liefert die abzuspielenden Samples (bereits mit Event-Lautstaerke skaliert)
+    ///
+    /// `None` wenn das Ereignis deaktiviert ist, der Client taub geschaltet
+    /// ist oder der eigene Beitritt weniger als die Karenzzeit zurueckliegt.
+    pub fn samples_fuer(
+        &self,
+        ereignis: SoundEreignis,
+        deafened: bool,
+        jetzt: Instant,
+    ) -> Option<Vec<f32>> {
+        if deafened || !self.einstellungen.aktiviert(ereignis) {
+            return None;
+        }
+        if let Some(beigetreten) = self.beigetreten_um {
+            if jetzt.saturating_duration_since(beigetreten) < KARENZZEIT_NACH_BEITRITT {
+                return None;
+            }
+        }
+        let faktor = self.einstellungen.lautstaerke_faktor();
+        if faktor <= 0.0 {
+            return None;
+        }
+        self.pack
+            .sound(ereignis)
+            .map(|s| s.iter().map(|v| v * faktor).collect())
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn eingebaute_sounds_vorhanden_und_kurz() {
+        let pack = SoundPack::eingebaut();
+        for e in SoundEreignis::ALLE {
+            let s = pack.sound(e).expect("Sound fehlt");
+            assert!(!s.is_empty());
+            assert!(s.len() <= (MAX_SOUND_DAUER_SEKUNDEN * SAMPLE_RATE) as usize);
+            assert!(s.iter().all(|v| v.abs() <= 1.0));
+        }
+    }
+
+    fn beitritt(user_id: &str, channel_id: &str) -> PraesenzEreignis {
+        PraesenzEreignis::ClientJoinedChannel {
+            user_id: user_id.into(),
+            channel_id: channel_id.into(),
+        }
+    }
+
+    fn abgang(user_id: &str, channel_id: &str) -> PraesenzEreignis {
+        PraesenzEreignis::ClientLeftChannel {
+            user_id: user_id.into(),
+            channel_id: channel_id.into(),
+        }
+    }
+
+    #[test]
+    fn praesenz_im_eigenen_kanal_spielt_sound() {
+        let mut sounds = EventSounds::default();
+        sounds.kanal_beigetreten("k1".into(), "ich".into(), Instant::now());
+        assert_eq!(
+            sounds.praesenz_sound(&beitritt("a", "k1")),
+            Some(SoundEreignis::Join)
+        );
+        assert_eq!(
+            sounds.praesenz_sound(&abgang("a", "k1")),
+            Some(SoundEreignis::Leave)
+        );
+    }
+
+    #[test]
+    fn praesenz_fremder_kanal_und_eigener_client_still() {
+        let mut sounds = EventSounds::default();
+        assert_eq!(sounds.praesenz_sound(&beitritt("a", "k1")), None);
+
+        sounds.kanal_beigetreten("k1".into(), "ich".into(), Instant::now());
+        assert_eq!(sounds.praesenz_sound(&beitritt("a", "k2")), None);
+        assert_eq!(sounds.praesenz_sound(&abgang("ich", "k1")), None);
+
+        sounds.kanal_verlassen();
+        assert_eq!(sounds.praesenz_sound(&abgang("a", "k1")), None);
+    }
+
+    #[test]
+    fn keine_sounds_waehrend_karenzzeit() {
+        let mut sounds = EventSounds::default();
+        let start = Instant::now();
+        sounds.kanal_beigetreten("k1".into(), "ich".into(), start);
+
+        assert!(sounds
+            .samples_fuer(
+                SoundEreignis::Join,
+                false,
+                start + Duration::from_millis(1500)
+            )
+            .is_none());
+        assert!(sounds
+            .samples_fuer(SoundEreignis::Join, false, start + KARENZZEIT_NACH_BEITRITT)
+            .is_some());
+    }
+
+    #[test]
+    fn keine_sounds_wenn_taub() {
+        let sounds = EventSounds::default();
+        assert!(sounds
+            .samples_fuer(SoundEreignis::Poke, true, Instant::now())
+            .is_none());
+    }
+
+    #[test]
+    fn deaktiviertes_ereignis_still() {
+        let mut sounds = EventSounds::default();
+        sounds
+            .einstellungen_setzen(EventSoundSettings {
+                leave: false,
+                ..Default::default()
+            })
+            .unwrap();
+        let jetzt = Instant::now();
+        assert!(sounds
+            .samples_fuer(SoundEreignis::Leave, false, jetzt)
+            .is_none());
+        assert!(sounds
+            .samples_fuer(SoundEreignis::Join, false, jetzt)
+            .is_some());
+    }
+
+    #[test]
+    fn event_lautstaerke_skaliert_samples() {
+        let mut sounds = EventSounds::default();
+        sounds
+            .einstellungen_setzen(EventSoundSettings {
+                volume: 100.0,
+                ..Default::default()
+            })
+            .unwrap();
+        let laut = sounds
+            .samples_fuer(SoundEreignis::Mention, false, Instant::now())
+            .unwrap();
+        sounds
+            .einstellungen_setzen(EventSoundSettings {
+                volume: 50.0,
+                ..Default::default()
+            })
+            .unwrap();
+        let leise = sounds
+            .samples_fuer(SoundEreignis::Mention, false, Instant::now())
+            .unwrap();
+        let max = |s: &[f32]| s.iter().fold(0.0f32, |m, v| m.max(v.abs()));
+        assert!((max(&leise) * 2.0 - max(&laut)).abs() < 1e-4);
+    }
+
+    #[test]
+    fn sound_pack_ungueltiges_verzeichnis() {
+        let mut sounds = EventSounds::default();
+        let ergebnis = sounds.einstellungen_setzen(EventSoundSettings {
+            sound_pack_dir: Some("/gibt/es/nicht".into()),
+            ..Default::default()
+        });
+        assert!(ergebnis.is_err());
+        assert_eq!(sounds.einstellungen().sound_pack_dir, None);
+    }
+
+    #[test]
+    fn dauer_pruefung() {
+        assert!(pruefe_dauer(48000 * 3, 48000).is_ok());
+        assert!(pruefe_dauer(48000 * 3 + 1, 48000).is_err());
+        assert!(pruefe_dauer(10, 0).is_err());
+    }
+
+    #[test]
+    fn resampling_verdoppelt_laenge() {
+        let s = resampeln(&[0.0, 1.0, 0.0, -1.0], 24000, 48000);
+        assert_eq!(s.len(), 8);
+        assert!((s[1] - 0.5).abs() < 1e-6);
+    }
+
+    #[test]
+    fn stereo_zu_mono() {
+        assert_eq!(zu_mono(&[1.0, 0.0, 0.5, 0.5], 2), vec![0.5, 0.5]);
+    }
+}
@@ -1,10 +1,27 @@
+mod beenden;
+mod benutzer_audio;
+mod chat_ereignisse;
 mod commands;
 mod connection;
+mod datei_download;
+mod datei_upload;
+mod einstellungen_speicher;
+mod entwuerfe;
+mod event_sounds;
+mod ptt;
+mod server_ping;
 mod state;
+mod validation;
 mod voice;
+mod voice_debug;
+mod voice_jitter;
+mod voice_krypto;
+mod voice_stats;
+mod voice_steuerung;
+mod voice_trace;
 
 use tauri::Manager;
-use tracing::info;
+use tracing::{info, warn};
 
 pub fn run() {
     tracing_subscriber::fmt()
@@ -18,17 +35,27 @@ pub fn run() {
 
     tauri::Builder::default()
         .plugin(tauri_plugin_updater::Builder::new().build())
-        .manage(state::AppState::mit_plugins())
+        .plugin(tauri_plugin_global_shortcut::Builder::new().build())
         .invoke_handler(tauri::generate_handler![
             commands::connect_to_server,
             commands::disconnect,
             commands::join_channel,
             commands::leave_channel,
+            commands::invite_to_channel,
+            commands::answer_channel_invite,
+            commands::knock_channel,
+            commands::answer_channel_knock,
             commands::get_audio_devices,
             commands::set_audio_config,
             commands::toggle_mute,
             commands::toggle_deafen,
+            commands::set_listen_only,
             commands::get_server_info,
+            commands::measure_server_latency,
+            commands::refresh_bookmark_latencies,
+            commands::get_bookmark_latencies,
+            commands::expand_channel,
+            commands::get_channel_members,
             // Channel-CRUD Commands (Phase 8.1)
             commands::create_channel,
             commands::edit_channel,
@@ -38,12 +65,51 @@ pub fn run() {
             commands::set_audio_settings,
             commands::start_calibration,
             commands::get_audio_stats,
+            commands::get_voice_diagnostics,
+            commands::get_connection_diagnostics,
+            commands::get_voice_debug_state,
+            commands::export_support_bundle,
+            commands::take_voice_events,
+            commands::apply_noise_suggestion,
+            commands::apply_jitter_mode,
             commands::play_test_sound,
+            // Event-Sounds
+            commands::get_event_sound_settings,
+            commands::set_event_sound_settings,
+            commands::play_event_sound,
+            commands::get_qos_settings,
+            commands::set_qos_settings,
+            commands::get_hardware_mute_settings,
+            commands::set_hardware_mute_settings,
+            commands::set_ptt_config,
+            commands::get_ptt_state,
+            commands::get_user_audio_preferences,
+            commands::set_user_audio_preferences,
+            commands::set_user_volume,
+            commands::set_user_muted,
+            commands::set_ssrc_volume,
+            commands::get_user_volumes,
+            commands::reset_user_audio_preferences,
+            commands::export_settings,
+            commands::import_settings,
+            commands::report_activity,
+            commands::start_voice_trace,
+            commands::stop_voice_trace,
+            commands::summarize_voice_trace,
             commands::start_audio_monitor,
             commands::stop_audio_monitor,
             // Chat-Commands (Phase 4)
             commands::send_message,
+            commands::get_message_limit,
+            commands::save_draft,
+            commands::get_draft,
+            commands::clear_draft,
+            commands::get_drafts,
+            commands::set_drafts,
             commands::get_message_history,
+            commands::stream_message_history,
+            commands::search_messages,
+            commands::resolve_display_names,
             commands::edit_message,
             commands::delete_message,
             commands::upload_file,
@@ -66,11 +132,41 @@ pub fn run() {
             commands::get_current_username,
         ])
         .setup(|app| {
+            // Gespeicherte Einstellungen laden, bevor das Frontend sie abfragt
+            let einstellungen = match app.path().app_config_dir() {
+                Ok(verzeichnis) => einstellungen_speicher::EinstellungsSpeicher::oeffnen(&verzeichnis),
+                Err(e) => {
+                    warn!("Kein Konfigurationsverzeichnis, Einstellungen werden nicht gespeichert: {}", e);
+                    einstellungen_speicher::EinstellungsSpeicher::fluechtig()
+                }
+            };
+            let audio = einstellungen.einstellungen().audio;
+            app.manage(state::AppState::mit_plugins(einstellungen));
+            if let Some(modus) = ptt::modus_aus_name(&audio.voice_mode) {
+                let state = app.state::<state::AppState>();
+                if let Err(e) = ptt::anwenden(app.handle(), &state.ptt, modus, audio.ptt_key) {
+                    warn!("Gespeicherte PTT-Taste nicht registriert: {}", e);
+                }
+            }
+
             let window = app.get_webview_window("main").unwrap();
             #[cfg(debug_assertions)]
             window.open_devtools();
             Ok(())
         })
+        .on_window_event(|window, event| {
+            // Fenster schliessen: erst geordnet abmelden, dann beenden
+            if let tauri::WindowEvent::CloseRequested { api, .. } = event {
+                api.prevent_close();
+                let app = window.app_handle().clone();
+                tauri::async_runtime::spawn(async move {
+                    let state = app.state::<state::AppState>();
+                    if beenden::herunterfahren(&state).await {
+                        app.exit(0);
+                    }
+                });
+            }
+        })
         .run(tauri::generate_context!())
         .expect("Fehler beim Starten der Tauri-Anwendung");
 }
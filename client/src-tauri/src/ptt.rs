@@ -0,0 +1,396 @@
+//! Push-to-Talk ueber eine globale Tastenkombination
+//!
+//! Die PTT-Taste aus den Audio-Einstellungen wird ueber das Global-Shortcut-
+//! Plugin systemweit registriert, funktioniert also auch, wenn das Fenster
+//! keinen Fokus hat. Jede Betaetigung laeuft durch den [`PttController`] der
+//! Audio-Engine; das Ergebnis liest der Sende-Loop je Frame aus der geteilten
+//! [`SendeFreigabe`].
+//!
+//! - **Hold**: gesendet wird, solange die Taste gedrueckt ist
+//! - **Toggle**: jeder Druck schaltet das Senden um
+//! - **Sprach-Erkennung**: keine Taste, es bleibt beim RMS-Gate des
+//!   Sende-Loops
+//!
+//! Die Tastennamen stammen aus dem Einstellungsdialog (z.B. `Strg + Leertaste`)
+//! und werden fuer das Plugin in dessen Schreibweise uebersetzt.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+use serde::Serialize;
+use speakeasy_audio::ptt::{PttController, PttMode};
+use tauri::{AppHandle, Emitter};
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut, ShortcutState};
+use tracing::{debug, info, warn};
+
+/// Name des Tauri-Events bei Wechsel des eigenen Sprech-Zustands
+pub const SPRECH_EREIGNIS: &str = "speaking_changed";
+
+/// Nutzdaten des Sprech-Events
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct SprechEreignis {
+    pub speaking: bool,
+}
+
+/// Meldet einen Wechsel des eigenen Sprech-Zustands an die Oberflaeche
+pub fn sprechen_melden(app: &AppHandle, speaking: bool) {
+    if let Err(e) = app.emit(SPRECH_EREIGNIS, SprechEreignis { speaking }) {
+        debug!("Sprech-Event konnte nicht gesendet werden: {}", e);
+    }
+}
+
+/// Sende-Freigabe, die der Sende-Loop je Frame liest
+#[derive(Debug, Default)]
+pub struct SendeFreigabe {
+    /// Push-to-Talk aktiv (Taste statt Sprach-Erkennung)
+    ptt: AtomicBool,
+    /// PTT-Taste gedrueckt bzw. eingeschaltet
+    aktiv: AtomicBool,
+}
+
+impl SendeFreigabe {
+    /// Push-to-Talk aktiv, aber die Taste nicht gedrueckt -> nicht senden
+    pub fn gesperrt(&self) -> bool {
+        self.ptt.load(Ordering::Relaxed) && !self.aktiv.load(Ordering::Relaxed)
+    }
+
+    /// Push-to-Talk statt Sprach-Erkennung?
+    pub fn ptt(&self) -> bool {
+        self.ptt.load(Ordering::Relaxed)
+    }
+}
+
+/// Aktueller PTT-Zustand fuer die Oberflaeche
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PttZustand {
+    pub mode: String,
+    pub key: Option<String>,
+    /// Darf gerade gesendet werden (Sprach-Erkennung: immer)
+    pub transmitting: bool,
+    /// Wird gerade Sprache gesendet
+    pub speaking: bool,
+}
+
+struct Innen {
+    controller: PttController,
+    /// Registrierte Taste (Name aus den Einstellungen)
+    taste: Option<String>,
+    /// Taste gerade unten (gegen Tastenwiederholung des Systems)
+    unten: bool,
+}
+
+/// PTT-Zustand des Clients, geteilt zwischen Shortcut-Handler und Sende-Loop
+pub struct PttSteuerung {
+    innen: Mutex<Innen>,
+    freigabe: Arc<SendeFreigabe>,
+}
+
+impl Default for PttSteuerung {
+    fn default() -> Self {
+        Self::neu()
+    }
+}
+
+impl PttSteuerung {
+    /// Startet mit Sprach-Erkennung und ohne Taste
+    pub fn neu() -> Self {
+        Self {
+            innen: Mutex::new(Innen {
+                controller: PttController::new(PttMode::VoiceActivation),
+                taste: None,
+                unten: false,
+            }),
+            freigabe: Arc::new(SendeFreigabe::default()),
+        }
+    }
+
+    /// Freigabe fuer den Sende-Loop
+    pub fn freigabe(&self) -> Arc<SendeFreigabe> {
+        Arc::clone(&self.freigabe)
+    }
+
+    /// Setzt Modus und Taste; jeder Wechsel beginnt ohne Senden
+    fn konfigurieren(&self, modus: PttMode, taste: Option<String>) {
+        let Ok(mut innen) = self.innen.lock() else {
+            return;
+        };
+        innen.controller.set_mode(modus);
+        innen.taste = taste;
+        innen.unten = false;
+        self.freigabe
+            .ptt
+            .store(modus != PttMode::VoiceActivation, Ordering::Relaxed);
+        self.freigabe.aktiv.store(false, Ordering::Relaxed);
+    }
+
+    /// Verarbeitet Druecken bzw. Loslassen der PTT-Taste
+    ///
+    /// Gibt den neuen Sende-Zustand zurueck, wenn er sich geaendert hat.
+    pub fn taste(&self, gedrueckt: bool) -> Option<bool> {
+        let mut innen = self.innen.lock().ok()?;
+        // Wiederholte Druck-Meldungen bei gehaltener Taste ignorieren
+        if innen.unten == gedrueckt {
+            return None;
+        }
+        innen.unten = gedrueckt;
+
+        let vorher = innen.controller.is_transmitting();
+        match (innen.controller.mode(), gedrueckt) {
+            (PttMode::Hold, true) => innen.controller.key_down(),
+            (PttMode::Hold, false) => innen.controller.key_up(),
+            (PttMode::Toggle, true) => innen.controller.toggle(),
+            _ => {}
+        }
+        let jetzt = innen.controller.is_transmitting();
+        self.freigabe.aktiv.store(jetzt, Ordering::Relaxed);
+        (jetzt != vorher).then_some(jetzt)
+    }
+
+    /// Aktueller Zustand fuer `get_ptt_state`
+    pub fn zustand(&self, speaking: bool) -> PttZustand {
+        let (modus, taste) = self
+            .innen
+            .lock()
+            .map(|i| (i.controller.mode(), i.taste.clone()))
+            .unwrap_or_default();
+        PttZustand {
+            mode: modus_name(modus).to_string(),
+            key: taste,
+            transmitting: !self.freigabe.gesperrt(),
+            speaking,
+        }
+    }
+
+    /// Sind Modus und Taste bereits so eingestellt?
+    fn ist_konfiguriert(&self, modus: PttMode, taste: &Option<String>) -> bool {
+        self.innen
+            .lock()
+            .is_ok_and(|i| i.controller.mode() == modus && &i.taste == taste)
+    }
+
+    fn taste_name(&self) -> Option<String> {
+        self.innen.lock().ok().and_then(|i| i.taste.clone())
+    }
+}
+
+/// Modus aus dem Namen der Einstellungen (`vad`, `ptt_hold`, `ptt_toggle`)
+pub fn modus_aus_name(name: &str) -> Option<PttMode> {
+    match name {
+        "vad" => Some(PttMode::VoiceActivation),
+        "ptt_hold" => Some(PttMode::Hold),
+        "ptt_toggle" => Some(PttMode::Toggle),
+        _ => None,
+    }
+}
+
+/// Name eines Modus in den Einstellungen
+pub fn modus_name(modus: PttMode) -> &'static str {
+    match modus {
+        PttMode::VoiceActivation => "vad",
+        PttMode::Hold => "ptt_hold",
+        PttMode::Toggle => "ptt_toggle",
+    }
+}
+
+/// Modifier in der Schreibweise des Plugins
+fn modifier_name(teil: &str) -> Option<&'static str> {
+    match teil {
+        "Strg" => Some("Control"),
+        "Alt" => Some("Alt"),
+        "Shift" => Some("Shift"),
+        "Meta" => Some("Super"),
+        _ => None,
+    }
+}
+
+/// Taste in der Schreibweise des Plugins (Buchstaben, Ziffern und F-Tasten
+/// heissen gleich)
+fn tasten_name(teil: &str) -> &str {
+    match teil {
+        "Leertaste" => "Space",
+        "Esc" => "Escape",
+        "Pfeil hoch" => "ArrowUp",
+        "Pfeil runter" => "ArrowDown",
+        "Pfeil links" => "ArrowLeft",
+        "Pfeil rechts" => "ArrowRight",
+        "Ruecktaste" => "Backspace",
+        "Entf" => "Delete",
+        "Einfg" => "Insert",
+        "Feststelltaste" => "CapsLock",
+        "Eingabe" => "Enter",
+        andere => andere,
+    }
+}
+
+/// Uebersetzt einen Tastennamen des Einstellungsdialogs in die Schreibweise
+/// des Global-Shortcut-Plugins (`Strg + Leertaste` -> `Control+Space`)
+pub fn tastenkuerzel(name: &str) -> Result<String, String> {
+    let mut teile = Vec::new();
+    let mut taste = None;
+    for teil in name.split('+').map(str::trim) {
+        if teil.is_empty() {
+            return Err(format!("Ungueltige PTT-Taste: {}", name));
+        }
+        if let Some(modifier) = modifier_name(teil) {
+            teile.push(modifier);
+        } else if taste.replace(tasten_name(teil)).is_some() {
+            return Err(format!("PTT-Taste hat mehr als eine Taste: {}", name));
+        }
+    }
+    let taste = taste.ok_or_else(|| {
+        format!(
+            "Eine Modifier-Taste allein ({}) kann nicht global registriert werden",
+            name
+        )
+    })?;
+    teile.push(taste);
+    Ok(teile.join("+"))
+}
+
+/// Tastenkuerzel fuer das Plugin
+fn shortcut(name: &str) -> Result<Shortcut, String> {
+    tastenkuerzel(name)?
+        .parse()
+        .map_err(|e| format!("PTT-Taste {} wird nicht unterstuetzt: {}", name, e))
+}
+
+/// Registriert die PTT-Taste systemweit und setzt den Modus
+///
+/// Eine zuvor registrierte Taste wird freigegeben. Im Modus
+/// Sprach-Erkennung wird keine Taste registriert.
+pub fn anwenden(
+    app: &AppHandle,
+    steuerung: &Arc<PttSteuerung>,
+    modus: PttMode,
+    taste: Option<String>,
+) -> Result<(), String> {
+    let taste = taste.filter(|_| modus != PttMode::VoiceActivation);
+    let neues_kuerzel = match (modus, &taste) {
+        (PttMode::VoiceActivation, _) => None,
+        (_, None) => return Err("Fuer Push-to-Talk ist keine Taste festgelegt".to_string()),
+        (_, Some(name)) => Some(shortcut(name)?),
+    };
+
+    // Unveraendert: Taste nicht neu registrieren, ein aktives Toggle bleibt
+    if steuerung.ist_konfiguriert(modus, &taste) {
+        return Ok(());
+    }
+
+    let shortcuts = app.global_shortcut();
+    if let Some(alt) = steuerung.taste_name().and_then(|name| shortcut(&name).ok()) {
+        if let Err(e) = shortcuts.unregister(alt) {
+            warn!("Alte PTT-Taste konnte nicht freigegeben werden: {}", e);
+        }
+    }
+
+    if let Some(kuerzel) = neues_kuerzel {
+        let handler_steuerung = Arc::clone(steuerung);
+        let registriert = shortcuts.on_shortcut(kuerzel, move |_app, _kuerzel, ereignis| {
+            let gedrueckt = ereignis.state() == ShortcutState::Pressed;
+            if let Some(sendet) = handler_steuerung.taste(gedrueckt) {
+                debug!("PTT: {}", if sendet { "senden" } else { "aus" });
+            }
+        });
+        if let Err(e) = registriert {
+            // Ohne Taste bleibt das Mikrofon im PTT-Modus zu
+            steuerung.konfigurieren(modus, None);
+            return Err(format!("PTT-Taste konnte nicht registriert werden: {}", e));
+        }
+    }
+    steuerung.konfigurieren(modus, taste.clone());
+
+    info!(
+        "Push-to-Talk: Modus {}, Taste {}",
+        modus_name(modus),
+        taste.as_deref().unwrap_or("-")
+    );
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn steuerung(modus: PttMode) -> PttSteuerung {
+        let s = PttSteuerung::neu();
+        s.konfigurieren(modus, Some("F".into()));
+        s
+    }
+
+    #[test]
+    fn sprach_erkennung_sperrt_nie() {
+        let s = steuerung(PttMode::VoiceActivation);
+        assert!(!s.freigabe().gesperrt());
+        assert!(!s.freigabe().ptt());
+        assert_eq!(s.taste(true), None);
+        assert!(!s.freigabe().gesperrt());
+    }
+
+    #[test]
+    fn hold_sendet_solange_gedrueckt() {
+        let s = steuerung(PttMode::Hold);
+        let freigabe = s.freigabe();
+        assert!(freigabe.gesperrt());
+        assert_eq!(s.taste(true), Some(true));
+        assert!(!freigabe.gesperrt());
+        // Tastenwiederholung aendert nichts
+        assert_eq!(s.taste(true), None);
+        assert_eq!(s.taste(false), Some(false));
+        assert!(freigabe.gesperrt());
+    }
+
+    #[test]
+    fn toggle_schaltet_bei_jedem_druck() {
+        let s = steuerung(PttMode::Toggle);
+        assert_eq!(s.taste(true), Some(true));
+        assert_eq!(s.taste(true), None);
+        assert_eq!(s.taste(false), None);
+        assert!(!s.freigabe().gesperrt());
+        assert_eq!(s.taste(true), Some(false));
+        assert_eq!(s.taste(false), None);
+        assert!(s.freigabe().gesperrt());
+    }
+
+    #[test]
+    fn moduswechsel_beginnt_ohne_senden() {
+        let s = steuerung(PttMode::Toggle);
+        s.taste(true);
+        s.konfigurieren(PttMode::Hold, Some("F".into()));
+        assert!(s.freigabe().gesperrt());
+        assert_eq!(
+            s.zustand(false),
+            PttZustand {
+                mode: "ptt_hold".into(),
+                key: Some("F".into()),
+                transmitting: false,
+                speaking: false,
+            }
+        );
+    }
+
+    #[test]
+    fn tastennamen_werden_uebersetzt() {
+        assert_eq!(tastenkuerzel("F").unwrap(), "F");
+        assert_eq!(tastenkuerzel("Strg + Leertaste").unwrap(), "Control+Space");
+        assert_eq!(
+            tastenkuerzel("Strg + Alt + Shift + Pfeil hoch").unwrap(),
+            "Control+Alt+Shift+ArrowUp"
+        );
+        assert_eq!(tastenkuerzel("Meta + F5").unwrap(), "Super+F5");
+        assert!(tastenkuerzel("Strg").is_err());
+        assert!(tastenkuerzel("").is_err());
+    }
+
+    #[test]
+    fn modus_namen_passen_zu_den_einstellungen() {
+        for modus in [PttMode::VoiceActivation, PttMode::Hold, PttMode::Toggle] {
+            assert_eq!(modus_aus_name(modus_name(modus)), Some(modus));
+        }
+        assert_eq!(modus_aus_name("ptt"), None);
+    }
+}
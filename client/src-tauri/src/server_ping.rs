@@ -0,0 +1,212 @@
+//! Server-Ping – Latenzmessung fuer die Serverliste
+//!
+//! Sendet einige [`PingPaket`]-Proben an den Voice-Port eines Servers und
+//! wertet die Antworten aus. Dafuer ist keine Anmeldung noetig; der Server
+//! beantwortet Pings ohne Session und drosselt sie pro Quell-IP.
+//!
+//! Jede Messung verwendet einen eigenen Socket, verspaetete Antworten einer
+//! frueheren Messung koennen also nicht falsch zugeordnet werden. Die
+//! Laufzeit misst der Client selbst; die Serverzeit der Antwort wird nicht
+//! benoetigt.
+
+use std::io;
+use std::net::{Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use speakeasy_protocol::voice::{PingPaket, PING_GROESSE};
+use tokio::net::UdpSocket;
+use tokio::time::Instant;
+
+/// Proben pro Messung
+pub const PROBEN: usize = 5;
+/// Abstand zwischen zwei Proben (unter dem Standard-Limit des Servers)
+pub const ABSTAND: Duration = Duration::from_millis(200);
+/// Wartezeit auf Antworten nach der letzten Probe
+pub const ZEITLIMIT: Duration = Duration::from_secs(1);
+
+/// Ergebnis einer Latenzmessung
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LatenzMessung {
+    /// Kleinste Laufzeit in Millisekunden (`None` = keine Antwort)
+    pub min_ms: Option<f64>,
+    /// Median der Laufzeiten in Millisekunden (`None` = keine Antwort)
+    pub median_ms: Option<f64>,
+    /// Anteil unbeantworteter Proben (0.0 - 1.0)
+    pub verlust: f64,
+    pub gesendet: u32,
+    pub empfangen: u32,
+}
+
+/// Wertet die Laufzeiten aus (`None` = Probe unbeantwortet)
+pub fn auswerten(proben: &[Option<Duration>]) -> LatenzMessung {
+    let mut laufzeiten: Vec<f64> = proben
+        .iter()
+        .flatten()
+        .map(|d| d.as_secs_f64() * 1000.0)
+        .collect();
+    laufzeiten.sort_by(|a, b| a.total_cmp(b));
+
+    let mitte = laufzeiten.len() / 2;
+    let median_ms = match laufzeiten.len() {
+        0 => None,
+        n if n % 2 == 1 => Some(laufzeiten[mitte]),
+        _ => Some((laufzeiten[mitte - 1] + laufzeiten[mitte]) / 2.0),
+    };
+    let gesendet = proben.len() as u32;
+    let empfangen = laufzeiten.len() as u32;
+    LatenzMessung {
+        min_ms: laufzeiten.first().copied(),
+        median_ms,
+        verlust: if gesendet == 0 {
+            0.0
+        } else {
+            f64::from(gesendet - empfangen) / f64::from(gesendet)
+        },
+        gesendet,
+        empfangen,
+    }
+}
+
+/// Misst die Latenz zum Voice-Port eines Servers
+///
+/// Fehler gibt es nur beim Aufloesen der Adresse und beim Binden des
+/// Sockets; nicht zustellbare Proben zaehlen als Verlust.
+pub async fn messen(
+    adresse: &str,
+    port: u16,
+    proben: usize,
+    abstand: Duration,
+    zeitlimit: Duration,
+) -> io::Result<LatenzMessung> {
+    let host = adresse.trim_start_matches('[').trim_end_matches(']');
+    let ziel = tokio::net::lookup_host((host, port))
+        .await?
+        .next()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "Adresse nicht aufloesbar"))?;
+    let lokal: SocketAddr = if ziel.is_ipv4() {
+        (Ipv4Addr::UNSPECIFIED, 0).into()
+    } else {
+        (Ipv6Addr::UNSPECIFIED, 0).into()
+    };
+    let socket = UdpSocket::bind(lokal).await?;
+    socket.connect(ziel).await?;
+
+    let start = Instant::now();
+    let mut gesendet_um: Vec<Option<Instant>> = vec![None; proben];
+    let mut laufzeiten: Vec<Option<Duration>> = vec![None; proben];
+    let mut naechste = 0;
+    let mut empfangen = 0;
+    let mut frist = start;
+    // Ein Byte mehr, damit zu lange Antworten beim Dekodieren auffallen
+    let mut buf = [0u8; PING_GROESSE + 1];
+
+    while naechste < proben || (empfangen < proben && Instant::now() < frist) {
+        tokio::select! {
+            _ = tokio::time::sleep_until(frist) => {
+                if naechste < proben {
+                    let anfrage = PingPaket::anfrage(
+                        naechste as u32,
+                        start.elapsed().as_millis() as u32,
+                    );
+                    if let Err(e) = socket.send(&anfrage.encode()).await {
+                        tracing::debug!(ziel = %ziel, fehler = %e, "Ping nicht gesendet");
+                    }
+                    gesendet_um[naechste] = Some(Instant::now());
+                    naechste += 1;
+                    frist += if naechste < proben { abstand } else { zeitlimit };
+                }
+            }
+            ergebnis = socket.recv(&mut buf) => {
+                // ICMP-Rueckmeldungen kommen als Fehler an: Probe gilt als verloren
+                let Ok(len) = ergebnis else { continue };
+                let Ok(antwort) = PingPaket::decode(&buf[..len]) else { continue };
+                let i = antwort.sequenz as usize;
+                if !antwort.antwort || i >= proben || laufzeiten[i].is_some() {
+                    continue;
+                }
+                if let Some(gesendet) = gesendet_um[i] {
+                    laufzeiten[i] = Some(gesendet.elapsed());
+                    empfangen += 1;
+                }
+            }
+        }
+    }
+
+    let messung = auswerten(&laufzeiten);
+    tracing::debug!(
+        ziel = %ziel,
+        median_ms = ?messung.median_ms,
+        verlust = messung.verlust,
+        "Server-Latenz gemessen"
+    );
+    Ok(messung)
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ms(wert: u64) -> Option<Duration> {
+        Some(Duration::from_millis(wert))
+    }
+
+    #[test]
+    fn auswertung_mit_verlust() {
+        let messung = auswerten(&[ms(30), None, ms(10), ms(20), None]);
+        assert_eq!(messung.min_ms, Some(10.0));
+        assert_eq!(messung.median_ms, Some(20.0));
+        assert_eq!(messung.gesendet, 5);
+        assert_eq!(messung.empfangen, 3);
+        assert!((messung.verlust - 0.4).abs() < 1e-9);
+
+        let gerade = auswerten(&[ms(40), ms(10), None, ms(20), ms(30)]);
+        assert_eq!(gerade.median_ms, Some(25.0));
+    }
+
+    #[test]
+    fn auswertung_ohne_antworten() {
+        let messung = auswerten(&[None, None, None]);
+        assert_eq!(messung.min_ms, None);
+        assert_eq!(messung.median_ms, None);
+        assert_eq!(messung.verlust, 1.0);
+
+        assert_eq!(auswerten(&[]).verlust, 0.0);
+    }
+
+    #[tokio::test]
+    async fn messung_gegen_verlustbehafteten_reflektor() {
+        // Beantwortet nur gerade Sequenznummern
+        let server = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let port = server.local_addr().unwrap().port();
+        tokio::spawn(async move {
+            let mut buf = [0u8; 64];
+            while let Ok((len, absender)) = server.recv_from(&mut buf).await {
+                let anfrage = PingPaket::decode(&buf[..len]).unwrap();
+                if anfrage.sequenz.is_multiple_of(2) {
+                    let antwort = anfrage.beantworten(1).encode();
+                    server.send_to(&antwort, absender).await.unwrap();
+                }
+            }
+        });
+
+        let messung = messen(
+            "127.0.0.1",
+            port,
+            4,
+            Duration::from_millis(5),
+            Duration::from_millis(200),
+        )
+        .await
+        .unwrap();
+        assert_eq!(messung.gesendet, 4);
+        assert_eq!(messung.empfangen, 2);
+        assert_eq!(messung.verlust, 0.5);
+        assert!(messung.min_ms.unwrap() <= messung.median_ms.unwrap());
+    }
+}
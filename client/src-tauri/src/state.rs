@@ -1,12 +1,22 @@
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
 use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
 use speakeasy_audio::engine::AudioEngineConfig;
 use speakeasy_plugin::manager::{ManagerKonfiguration, PluginManager};
 use tokio::sync::Mutex as AsyncMutex;
 
+use crate::benutzer_audio::BenutzerPegel;
 use crate::connection::ServerConnection;
+use crate::einstellungen_speicher::EinstellungsSpeicher;
+use crate::entwuerfe::Entwuerfe;
+use crate::event_sounds::EventSounds;
+use crate::ptt::PttSteuerung;
+use crate::server_ping::LatenzMessung;
 use crate::voice::VoiceClient;
+use crate::voice_krypto::VoiceSchluessel;
+use crate::voice_trace::VoiceTrace;
 
 /// Verbindungszustand des Clients (leichtgewichtige Metadaten)
 #[derive(Debug, Default)]
@@ -18,6 +28,8 @@ pub struct ConnectionState {
     pub current_channel: Option<String>,
     /// Ob der Benutzer sein Passwort zwingend aendern muss
     pub force_password_change: bool,
+    /// Vom Server gemeldete Hoechstlaenge einer Chat-Nachricht (Zeichen)
+    pub max_message_chars: Option<usize>,
 }
 
 /// Echtzeit-Pegel vom Audio-Monitor (lock-free lesbar)
@@ -57,6 +69,8 @@ impl Drop for AudioMonitor {
 pub struct AudioState {
     pub muted: bool,
     pub deafened: bool,
+    /// Mikrofon per Headset-Taste bzw. im System stumm (erkannt)
+    pub hardware_muted: bool,
     /// Aktuelle Audio-Engine-Konfiguration (gespeicherte Einstellungen)
     pub engine_config: Option<AudioEngineConfig>,
     /// Vollstaendige Audio-Einstellungen vom Frontend (inkl. DSP, Codec, Jitter)
@@ -65,6 +79,33 @@ pub struct AudioState {
     pub monitor: Option<AudioMonitor>,
 }
 
+/// Mindestabstand zwischen zwei Aktivitaetsmeldungen an den Server
+pub const AKTIVITAET_INTERVALL: Duration = Duration::from_secs(60);
+
+/// Drosselt Aktivitaetsmeldungen auf hoechstens eine pro Intervall
+#[derive(Debug, Default)]
+pub struct AktivitaetsDrossel {
+    letzte_meldung: Option<Instant>,
+}
+
+impl AktivitaetsDrossel {
+    /// Gibt true zurueck (und merkt sich den Zeitpunkt) wenn gemeldet werden soll
+    pub fn faellig(&mut self, jetzt: Instant) -> bool {
+        let faellig = self
+            .letzte_meldung
+            .is_none_or(|t| jetzt.saturating_duration_since(t) >= AKTIVITAET_INTERVALL);
+        if faellig {
+            self.letzte_meldung = Some(jetzt);
+        }
+        faellig
+    }
+
+    /// Setzt die Drossel zurueck (z.B. nach neuer Verbindung)
+    pub fn zuruecksetzen(&mut self) {
+        self.letzte_meldung = None;
+    }
+}
+
 /// Globaler Anwendungszustand (Mutex-gesichert fuer Thread-Sicherheit)
 pub struct AppState {
     /// Leichtgewichtige Verbindungs-Metadaten (sync, fuer einfache Checks)
@@ -75,6 +116,32 @@ pub struct AppState {
     pub plugin_manager: Mutex<Option<PluginManager>>,
     /// Voice-Client (async Mutex, da start/stop async sind)
     pub voice: AsyncMutex<Option<VoiceClient>>,
+    /// Event-Sounds (Einstellungen, Sound-Pack, Presence-Abgleich)
+    pub event_sounds: Mutex<EventSounds>,
+    /// DSCP-Markierung fuer Voice- und Control-Sockets
+    pub qos: Mutex<crate::commands::QosSettings>,
+    /// Erkennung der Hardware-Stummschaltung (Headset-Taste, System-Mute)
+    pub hardware_stumm: Mutex<crate::commands::HardwareMuteSettings>,
+    /// Push-to-Talk (globale Taste), ueberlebt Kanalwechsel
+    pub ptt: Arc<PttSteuerung>,
+    /// Drossel fuer Aktivitaetsmeldungen (AFK-Erkennung)
+    pub aktivitaet: Mutex<AktivitaetsDrossel>,
+    /// Netzwerk-Debugmodus (Paket-Trace), ueberlebt Kanalwechsel
+    pub voice_trace: Arc<VoiceTrace>,
+    /// E2E-Schluessel der Voice-Pakete, geteilt mit Verbindung und Voice-Client
+    pub voice_schluessel: Arc<VoiceSchluessel>,
+    /// Lautstaerke und Stummschaltung pro Benutzer, ueberlebt Verbindungen
+    pub benutzer_pegel: Arc<BenutzerPegel>,
+    /// Entwuerfe des Eingabefelds pro Kanal, ueberstehen ein Neuladen der Webview
+    pub entwuerfe: Entwuerfe,
+    /// Auf der Festplatte gespeicherte Client-Einstellungen (Audio)
+    pub einstellungen: EinstellungsSpeicher,
+    /// Vom Server vorgegebene Ziel-Bitrate in kbps (0 = keine Vorgabe)
+    pub ziel_bitrate: Arc<AtomicU32>,
+    /// Zuletzt gemessene Latenz je Server (`adresse:port`)
+    pub server_latenzen: Mutex<HashMap<String, LatenzMessung>>,
+    /// Der Client wird beendet (siehe `beenden::herunterfahren`)
+    pub beendet: AtomicBool,
 }
 
 impl Default for AppState {
@@ -85,20 +152,72 @@ impl Default for AppState {
             audio: Mutex::new(AudioState::default()),
             plugin_manager: Mutex::new(None),
             voice: AsyncMutex::new(None),
+            event_sounds: Mutex::new(EventSounds::default()),
+            qos: Mutex::new(Default::default()),
+            hardware_stumm: Mutex::new(Default::default()),
+            ptt: Arc::new(PttSteuerung::neu()),
+            aktivitaet: Mutex::new(AktivitaetsDrossel::default()),
+            voice_trace: Arc::new(VoiceTrace::new()),
+            voice_schluessel: Arc::new(VoiceSchluessel::neu()),
+            benutzer_pegel: Arc::new(BenutzerPegel::neu()),
+            entwuerfe: Entwuerfe::neu(),
+            einstellungen: EinstellungsSpeicher::fluechtig(),
+            ziel_bitrate: Arc::new(AtomicU32::new(0)),
+            server_latenzen: Mutex::new(HashMap::new()),
+            beendet: AtomicBool::new(false),
         }
     }
 }
 
 impl AppState {
     /// Erstellt einen neuen AppState mit initialisiertem PluginManager
-    pub fn mit_plugins() -> Self {
+    ///
+    /// Uebernimmt die gespeicherten Audio-Einstellungen aus `einstellungen`.
+    pub fn mit_plugins(einstellungen: EinstellungsSpeicher) -> Self {
         let manager = PluginManager::neu(ManagerKonfiguration::default());
+        let audio = einstellungen.einstellungen().audio;
+        let ptt_modus = crate::ptt::modus_aus_name(&audio.voice_mode).unwrap_or_default();
         Self {
             connection: Mutex::new(ConnectionState::default()),
             tcp: AsyncMutex::new(None),
-            audio: Mutex::new(AudioState::default()),
+            audio: Mutex::new(AudioState {
+                engine_config: Some(crate::commands::engine_config_fuer(None, &audio, ptt_modus)),
+                full_settings: Some(audio),
+                ..AudioState::default()
+            }),
             plugin_manager: Mutex::new(Some(manager)),
             voice: AsyncMutex::new(None),
+            event_sounds: Mutex::new(EventSounds::default()),
+            qos: Mutex::new(Default::default()),
+            hardware_stumm: Mutex::new(Default::default()),
+            ptt: Arc::new(PttSteuerung::neu()),
+            aktivitaet: Mutex::new(AktivitaetsDrossel::default()),
+            voice_trace: Arc::new(VoiceTrace::new()),
+            voice_schluessel: Arc::new(VoiceSchluessel::neu()),
+            benutzer_pegel: Arc::new(BenutzerPegel::neu()),
+            entwuerfe: Entwuerfe::neu(),
+            einstellungen,
+            ziel_bitrate: Arc::new(AtomicU32::new(0)),
+            server_latenzen: Mutex::new(HashMap::new()),
+            beendet: AtomicBool::new(false),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn drossel_meldet_hoechstens_einmal_pro_intervall() {
+        let mut drossel = AktivitaetsDrossel::default();
+        let start = Instant::now();
+
+        assert!(drossel.faellig(start));
+        assert!(!drossel.faellig(start + Duration::from_secs(59)));
+        assert!(drossel.faellig(start + AKTIVITAET_INTERVALL));
+
+        drossel.zuruecksetzen();
+        assert!(drossel.faellig(start + AKTIVITAET_INTERVALL));
+    }
+}
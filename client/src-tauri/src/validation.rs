@@ -0,0 +1,1071 @@
+//! Eingabe-Validierung fuer Tauri-Commands
+//!
+//! Alle Werte aus dem Webview gelten als nicht vertrauenswuerdig. Jeder
+//! Command prueft seine Parameter hier, bevor er Netzwerk, Dateisystem oder
+//! Plugin-Manager anfasst. Die Grenzen entsprechen – soweit vorhanden – den
+//! Server-Limits, damit Fehler lokal und sofort gemeldet werden.
+
+use std::path::{Path, PathBuf};
+
+use speakeasy_audio::hardware_stumm::{MAX_NULL_DAUER, MIN_NULL_DAUER};
+use speakeasy_audio::ptt::PttMode;
+use speakeasy_core::types::{ChannelId, UserId};
+use speakeasy_protocol::control::MAX_NACHRICHT_ZEICHEN;
+use speakeasy_protocol::qos::DSCP_MAX;
+
+use crate::benutzer_audio::{BenutzerAudioEinstellungen, MAX_GAIN};
+use crate::commands::{
+    AudioConfig, AudioSettingsConfig, HardwareMuteSettings, QosSettings, ServerZiel,
+};
+use crate::entwuerfe::{EntwurfsEinstellungen, MAX_ENTWURF_ZEICHEN};
+use crate::event_sounds::EventSoundSettings;
+
+// ---------------------------------------------------------------------------
+// Grenzen
+// ---------------------------------------------------------------------------
+
+/// Maximale Laenge eines Hostnamens (DNS)
+pub const MAX_ADRESSE: usize = 253;
+/// Maximale Laenge von Benutzer- und Anzeigenamen (Server: Nickname <= 64)
+pub const MAX_BENUTZERNAME: usize = 64;
+/// Maximale Passwortlaenge
+pub const MAX_PASSWORT: usize = 1024;
+/// Maximale Laenge einer Chat-Nachricht in Zeichen (Server meldet seinen
+/// Wert in `Welcome`, dieser gilt ohne Verbindung)
+pub const MAX_NACHRICHT: usize = MAX_NACHRICHT_ZEICHEN;
+/// Maximale Laenge einer Abwesenheitsnachricht
+pub const MAX_ABWESENHEIT: usize = 500;
+/// Maximale Laenge einer Einladungs- oder Anklopfnachricht (Server: 200)
+pub const MAX_EINLADUNGSNACHRICHT: usize = 200;
+/// Maximale Laenge von IDs (UUIDs und Server-IDs)
+pub const MAX_ID: usize = 64;
+/// Maximale Laenge eines Kanalnamens
+pub const MAX_KANALNAME: usize = 64;
+/// Maximale Laenge einer Kanalbeschreibung
+pub const MAX_KANALBESCHREIBUNG: usize = 1024;
+/// Maximale Anzahl Clients pro Kanal
+pub const MAX_KANAL_CLIENTS: u32 = 1024;
+/// Maximale Laenge eines Dateinamens
+pub const MAX_DATEINAME: usize = 255;
+/// Maximale Laenge eines MIME-Typs
+pub const MAX_MIME_TYP: usize = 255;
+/// Maximale Upload-Groesse (Server-Standardquota: 10 MB)
+pub const MAX_UPLOAD_BYTES: usize = 10 * 1024 * 1024;
+/// Maximale Anzahl Nachrichten pro History-Abfrage (Server: 100)
+pub const MAX_HISTORY_LIMIT: u32 = 100;
+/// Maximale Laenge eines Suchtexts (Server: 200 Zeichen)
+pub const MAX_SUCHTEXT: usize = 200;
+/// Maximale Laenge eines Pfades
+pub const MAX_PFAD: usize = 4096;
+/// Maximale Laenge kurzer Einstellungs-Strings (Geraete-IDs, Modi, Tasten)
+pub const MAX_EINSTELLUNG: usize = 256;
+/// Maximale Groesse eines Voice-Traces in MB
+pub const MAX_TRACE_MB: u32 = 1024;
+/// Maximale Aufbewahrung gespeicherter Lautstaerken pro Benutzer (Tage)
+pub const MAX_AUFBEWAHRUNG_TAGE: u32 = 3650;
+/// Maximale Anzahl Server pro Latenz-Aktualisierung
+pub const MAX_LATENZ_SERVER: usize = 64;
+/// Maximale Anzahl IDs pro Namensaufloesung (Server: 100 pro Anfrage)
+pub const MAX_NAMEN_IDS: usize = 1000;
+
+// ---------------------------------------------------------------------------
+// Fehler-Typ
+// ---------------------------------------------------------------------------
+
+/// Fehler bei der Validierung einer Command-Eingabe
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ValidationError {
+    /// Pflichtfeld ist leer
+    Leer { feld: &'static str },
+    /// Text ueberschreitet die maximale Laenge (in Bytes)
+    ZuLang {
+        feld: &'static str,
+        max: usize,
+        ist: usize,
+    },
+    /// Binaerdaten ueberschreiten die maximale Groesse
+    ZuGross {
+        feld: &'static str,
+        max: usize,
+        ist: usize,
+    },
+    /// Wert hat ein ungueltiges Format oder liegt ausserhalb des Bereichs
+    Ungueltig { feld: &'static str, grund: String },
+    /// Pfad ist nicht zulaessig
+    Pfad { feld: &'static str, grund: String },
+}
+
+impl std::fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ValidationError::Leer { feld } => write!(f, "{} darf nicht leer sein", feld),
+            ValidationError::ZuLang { feld, max, ist } => {
+                write!(
+                    f,
+                    "{} ist zu lang ({} von maximal {} Zeichen)",
+                    feld, ist, max
+                )
+            }
+            ValidationError::ZuGross { feld, max, ist } => {
+                write!(
+                    f,
+                    "{} ist zu gross ({} von maximal {} Bytes)",
+                    feld, ist, max
+                )
+            }
+            ValidationError::Ungueltig { feld, grund } => {
+                write!(f, "{} ist ungueltig: {}", feld, grund)
+            }
+            ValidationError::Pfad { feld, grund } => {
+                write!(f, "{} ist nicht zulaessig: {}", feld, grund)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ValidationError {}
+
+impl From<ValidationError> for String {
+    fn from(e: ValidationError) -> Self {
+        e.to_string()
+    }
+}
+
+pub type Ergebnis<T = ()> = Result<T, ValidationError>;
+
+// ---------------------------------------------------------------------------
+// Grundbausteine
+// ---------------------------------------------------------------------------
+
+/// Prueft die Laenge eines Textes und verbietet Steuerzeichen (ausser \n, \t)
+pub fn text(feld: &'static str, wert: &str, max: usize) -> Ergebnis {
+    if wert.len() > max {
+        return Err(ValidationError::ZuLang {
+            feld,
+            max,
+            ist: wert.len(),
+        });
+    }
+    ohne_steuerzeichen(feld, wert)
+}
+
+/// Wie `text`, die Grenze gilt aber in Zeichen statt Bytes (Chat-Inhalte)
+pub fn zeichen(feld: &'static str, wert: &str, max: usize) -> Ergebnis {
+    let ist = wert.chars().count();
+    if ist > max {
+        return Err(ValidationError::ZuLang { feld, max, ist });
+    }
+    ohne_steuerzeichen(feld, wert)
+}
+
+fn ohne_steuerzeichen(feld: &'static str, wert: &str) -> Ergebnis {
+    if wert
+        .chars()
+        .any(|c| c.is_control() && c != '\n' && c != '\t')
+    {
+        return Err(ValidationError::Ungueltig {
+            feld,
+            grund: "enthaelt Steuerzeichen".to_string(),
+        });
+    }
+    Ok(())
+}
+
+/// Vereinheitlicht Zeilenumbrueche mehrzeiliger Eingaben auf `\n`
+///
+/// Eingabefelder liefern je nach System `\r\n` oder `\r`; `\r` gilt sonst
+/// als Steuerzeichen.
+pub fn zeilenumbrueche(wert: String) -> String {
+    if wert.contains('\r') {
+        wert.replace("\r\n", "\n").replace('\r', "\n")
+    } else {
+        wert
+    }
+}
+
+/// Wie `text`, zusaetzlich darf der Wert nicht leer sein
+pub fn pflichttext(feld: &'static str, wert: &str, max: usize) -> Ergebnis {
+    if wert.trim().is_empty() {
+        return Err(ValidationError::Leer { feld });
+    }
+    text(feld, wert, max)
+}
+
+/// Wie `text` fuer optionale Werte
+pub fn optionaler_text(feld: &'static str, wert: Option<&str>, max: usize) -> Ergebnis {
+    wert.map_or(Ok(()), |w| text(feld, w, max))
+}
+
+/// Prueft die Groesse von Binaerdaten
+pub fn bytes(feld: &'static str, daten: &[u8], max: usize) -> Ergebnis {
+    if daten.len() > max {
+        return Err(ValidationError::ZuGross {
+            feld,
+            max,
+            ist: daten.len(),
+        });
+    }
+    Ok(())
+}
+
+/// Prueft eine einfache ID (keine Leerzeichen, nur druckbare ASCII-Zeichen)
+pub fn id(feld: &'static str, wert: &str) -> Ergebnis {
+    pflichttext(feld, wert, MAX_ID)?;
+    if !wert.chars().all(|c| c.is_ascii_graphic()) {
+        return Err(ValidationError::Ungueltig {
+            feld,
+            grund: "nur druckbare ASCII-Zeichen erlaubt".to_string(),
+        });
+    }
+    Ok(())
+}
+
+/// Prueft eine UUID und gibt sie zurueck
+pub fn uuid(feld: &'static str, wert: &str) -> Ergebnis<::uuid::Uuid> {
+    id(feld, wert)?;
+    ::uuid::Uuid::parse_str(wert).map_err(|_| ValidationError::Ungueltig {
+        feld,
+        grund: "keine gueltige UUID".to_string(),
+    })
+}
+
+/// Prueft eine Kanal-ID
+pub fn kanal_id(wert: &str) -> Ergebnis<ChannelId> {
+    uuid("Kanal-ID", wert).map(ChannelId)
+}
+
+/// Prueft eine Zahl auf einen Bereich
+pub fn bereich<T: PartialOrd + std::fmt::Display>(
+    feld: &'static str,
+    wert: T,
+    min: T,
+    max: T,
+) -> Ergebnis {
+    if wert < min || wert > max {
+        return Err(ValidationError::Ungueltig {
+            feld,
+            grund: format!("{} liegt nicht zwischen {} und {}", wert, min, max),
+        });
+    }
+    Ok(())
+}
+
+/// Prueft eine Float-Einstellung (endlich und im Bereich)
+pub fn float_bereich(feld: &'static str, wert: f32, min: f32, max: f32) -> Ergebnis {
+    if !wert.is_finite() {
+        return Err(ValidationError::Ungueltig {
+            feld,
+            grund: "keine endliche Zahl".to_string(),
+        });
+    }
+    bereich(feld, wert, min, max)
+}
+
+/// Prueft eine Server-Adresse (Hostname, IPv4 oder IPv6)
+pub fn adresse(wert: &str) -> Ergebnis {
+    const FELD: &str = "Server-Adresse";
+    pflichttext(FELD, wert, MAX_ADRESSE)?;
+    let erlaubt =
+        |c: char| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_' | ':' | '[' | ']');
+    if !wert.chars().all(erlaubt) {
+        return Err(ValidationError::Ungueltig {
+            feld: FELD,
+            grund: "nur Hostname oder IP-Adresse erlaubt".to_string(),
+        });
+    }
+    Ok(())
+}
+
+/// Prueft einen Port (0 ist nicht erlaubt)
+pub fn port(wert: u16) -> Ergebnis {
+    if wert == 0 {
+        return Err(ValidationError::Ungueltig {
+            feld: "Port",
+            grund: "Port 0 ist nicht erlaubt".to_string(),
+        });
+    }
+    Ok(())
+}
+
+/// Prueft einen Dateinamen (kein Pfad, keine reservierten Zeichen)
+pub fn dateiname(wert: &str) -> Ergebnis {
+    const FELD: &str = "Dateiname";
+    pflichttext(FELD, wert, MAX_DATEINAME)?;
+    if wert.contains(['/', '\\', ':']) || wert == "." || wert == ".." {
+        return Err(ValidationError::Ungueltig {
+            feld: FELD,
+            grund: "darf keinen Pfad enthalten".to_string(),
+        });
+    }
+    Ok(())
+}
+
+/// Prueft einen MIME-Typ (`typ/subtyp`)
+pub fn mime_typ(wert: &str) -> Ergebnis {
+    const FELD: &str = "MIME-Typ";
+    pflichttext(FELD, wert, MAX_MIME_TYP)?;
+    let gueltig = wert
+        .split_once('/')
+        .is_some_and(|(typ, subtyp)| !typ.is_empty() && !subtyp.is_empty())
+        && wert.chars().all(|c| c.is_ascii_graphic());
+    if !gueltig {
+        return Err(ValidationError::Ungueltig {
+            feld: FELD,
+            grund: "erwartet Format typ/subtyp".to_string(),
+        });
+    }
+    Ok(())
+}
+
+/// Erwartete Art eines Pfades
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PfadArt {
+    Datei,
+    Verzeichnis,
+}
+
+/// Prueft und kanonisiert einen Pfad
+///
+/// Netzwerkpfade (UNC, `\\?\`-Praefixe) werden abgelehnt; der Pfad muss
+/// existieren und die erwartete Art haben. Zurueckgegeben wird der
+/// kanonische Pfad, der anstelle der Eingabe verwendet werden muss.
+pub fn pfad(feld: &'static str, wert: &str, art: PfadArt) -> Ergebnis<PathBuf> {
+    pflichttext(feld, wert, MAX_PFAD)?;
+    let fehler = |grund: &str| ValidationError::Pfad {
+        feld,
+        grund: grund.to_string(),
+    };
+
+    if wert.starts_with("\\\\") || wert.starts_with("//") {
+        return Err(fehler("Netzwerkpfade sind nicht erlaubt"));
+    }
+
+    let kanonisch = Path::new(wert)
+        .canonicalize()
+        .map_err(|_| fehler("Pfad existiert nicht"))?;
+
+    // Unter Windows liefert canonicalize `\\?\C:\...`; Netzwerkfreigaben
+    // erscheinen als `\\?\UNC\...` und werden hier abgelehnt.
+    let anzeige = kanonisch.to_string_lossy();
+    if anzeige.starts_with(r"\\?\UNC\") || anzeige.starts_with(r"\\.\") {
+        return Err(fehler("Netzwerk- und Geraetepfade sind nicht erlaubt"));
+    }
+
+    match art {
+        PfadArt::Datei if !kanonisch.is_file() => Err(fehler("ist keine Datei")),
+        PfadArt::Verzeichnis if !kanonisch.is_dir() => Err(fehler("ist kein Verzeichnis")),
+        _ => Ok(kanonisch),
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Command-spezifische Pruefungen
+// ---------------------------------------------------------------------------
+
+/// connect_to_server
+pub fn verbindung(address: &str, port_nr: u16, username: &str, password: Option<&str>) -> Ergebnis {
+    adresse(address)?;
+    port(port_nr)?;
+    pflichttext("Benutzername", username, MAX_BENUTZERNAME)?;
+    optionaler_text("Passwort", password, MAX_PASSWORT)
+}
+
+/// measure_server_latency
+pub fn latenz_ziel(address: &str, port_nr: u16) -> Ergebnis {
+    adresse(address)?;
+    port(port_nr)
+}
+
+/// refresh_bookmark_latencies
+pub fn latenz_ziele(ziele: &[ServerZiel]) -> Ergebnis {
+    bereich("Anzahl Server", ziele.len(), 0, MAX_LATENZ_SERVER)?;
+    ziele
+        .iter()
+        .try_for_each(|ziel| latenz_ziel(&ziel.address, ziel.port))
+}
+
+/// create_channel
+pub fn kanal_erstellen(
+    name: &str,
+    description: Option<&str>,
+    password: Option<&str>,
+    max_clients: Option<u32>,
+    parent_id: Option<&str>,
+) -> Ergebnis<Option<ChannelId>> {
+    pflichttext("Kanalname", name, MAX_KANALNAME)?;
+    kanal_attribute(description, password, max_clients)?;
+    parent_id.map(kanal_id).transpose()
+}
+
+/// edit_channel
+pub fn kanal_bearbeiten(
+    channel_id: &str,
+    name: Option<&str>,
+    description: Option<&str>,
+    password: Option<&str>,
+    max_clients: Option<u32>,
+) -> Ergebnis<ChannelId> {
+    let cid = kanal_id(channel_id)?;
+    if let Some(name) = name {
+        pflichttext("Kanalname", name, MAX_KANALNAME)?;
+    }
+    kanal_attribute(description, password, max_clients)?;
+    Ok(cid)
+}
+
+fn kanal_attribute(
+    description: Option<&str>,
+    password: Option<&str>,
+    max_clients: Option<u32>,
+) -> Ergebnis {
+    optionaler_text("Kanalbeschreibung", description, MAX_KANALBESCHREIBUNG)?;
+    optionaler_text("Kanalpasswort", password, MAX_PASSWORT)?;
+    if let Some(max) = max_clients {
+        bereich("Maximale Clients", max, 0, MAX_KANAL_CLIENTS)?;
+    }
+    Ok(())
+}
+
+/// send_message (`max_zeichen`: Grenze des verbundenen Servers)
+pub fn nachricht_senden(
+    channel_id: &str,
+    content: &str,
+    reply_to: Option<&str>,
+    max_zeichen: usize,
+) -> Ergebnis<ChannelId> {
+    let cid = kanal_id(channel_id)?;
+    nachrichtentext(content, max_zeichen)?;
+    if let Some(r) = reply_to {
+        id("Antwort-ID", r)?;
+    }
+    Ok(cid)
+}
+
+/// edit_message
+pub fn nachricht_bearbeiten(message_id: &str, content: &str, max_zeichen: usize) -> Ergebnis {
+    id("Nachrichten-ID", message_id)?;
+    nachrichtentext(content, max_zeichen)
+}
+
+fn nachrichtentext(content: &str, max_zeichen: usize) -> Ergebnis {
+    if content.trim().is_empty() {
+        return Err(ValidationError::Leer { feld: "Nachricht" });
+    }
+    zeichen("Nachricht", content, max_zeichen)
+}
+
+/// save_draft (leerer Text ist erlaubt und entfernt den Entwurf)
+pub fn entwurf(channel_id: &str, text: &str) -> Ergebnis<ChannelId> {
+    let cid = kanal_id(channel_id)?;
+    zeichen("Entwurf", text, MAX_ENTWURF_ZEICHEN)?;
+    Ok(cid)
+}
+
+/// set_drafts, import_settings
+pub fn entwuerfe(config: &EntwurfsEinstellungen) -> Ergebnis {
+    config
+        .drafts
+        .values()
+        .try_for_each(|e| zeichen("Entwurf", &e.text, MAX_ENTWURF_ZEICHEN))
+}
+
+/// get_message_history
+pub fn nachrichten_verlauf(
+    channel_id: &str,
+    before: Option<&str>,
+    limit: Option<u32>,
+) -> Ergebnis<ChannelId> {
+    let cid = kanal_id(channel_id)?;
+    if let Some(b) = before {
+        id("Zeitpunkt", b)?;
+    }
+    if let Some(l) = limit {
+        bereich("Limit", l, 1, MAX_HISTORY_LIMIT)?;
+    }
+    Ok(cid)
+}
+
+/// resolve_display_names
+pub fn namen_aufloesen(
+    user_ids: &[String],
+    channel_ids: &[String],
+) -> Ergebnis<(Vec<UserId>, Vec<ChannelId>)> {
+    bereich(
+        "Anzahl IDs",
+        user_ids.len() + channel_ids.len(),
+        0,
+        MAX_NAMEN_IDS,
+    )?;
+    let benutzer = user_ids
+        .iter()
+        .map(|id| uuid("Benutzer-ID", id).map(UserId))
+        .collect::<Ergebnis<_>>()?;
+    let kanaele = channel_ids
+        .iter()
+        .map(|id| kanal_id(id))
+        .collect::<Ergebnis<_>>()?;
+    Ok((benutzer, kanaele))
+}
+
+/// search_messages
+pub fn nachrichten_suchen(
+    channel_id: &str,
+    query: &str,
+    before: Option<&str>,
+    before_id: Option<&str>,
+    limit: Option<u32>,
+) -> Ergebnis<ChannelId> {
+    pflichttext("Suchtext", query, MAX_SUCHTEXT)?;
+    if let Some(before_id) = before_id {
+        id("Nachrichten-ID", before_id)?;
+    }
+    nachrichten_verlauf(channel_id, before, limit)
+}
+
+/// upload_file
+pub fn datei_upload(
+    channel_id: &str,
+    filename: &str,
+    mime_type: &str,
+    data: &[u8],
+) -> Ergebnis<ChannelId> {
+    bytes("Datei", data, MAX_UPLOAD_BYTES)?;
+    let cid = kanal_id(channel_id)?;
+    dateiname(filename)?;
+    mime_typ(mime_type)?;
+    Ok(cid)
+}
+
+/// change_password
+pub fn passwort_aendern(old_password: &str, new_password: &str) -> Ergebnis {
+    pflichttext("Altes Passwort", old_password, MAX_PASSWORT)?;
+    pflichttext("Neues Passwort", new_password, MAX_PASSWORT)
+}
+
+/// change_nickname
+pub fn nickname(new_nickname: &str) -> Ergebnis {
+    pflichttext("Nickname", new_nickname, MAX_BENUTZERNAME)
+}
+
+/// set_away
+pub fn abwesenheit(message: Option<&str>) -> Ergebnis {
+    optionaler_text("Abwesenheitsnachricht", message, MAX_ABWESENHEIT)
+}
+
+/// set_audio_config
+pub fn audio_config(config: &AudioConfig) -> Ergebnis {
+    optionaler_text(
+        "Eingabegeraet",
+        config.input_device_id.as_deref(),
+        MAX_EINSTELLUNG,
+    )?;
+    optionaler_text(
+        "Ausgabegeraet",
+        config.output_device_id.as_deref(),
+        MAX_EINSTELLUNG,
+    )?;
+    float_bereich("Eingangslautstaerke", config.input_volume, 0.0, 200.0)?;
+    float_bereich("Ausgangslautstaerke", config.output_volume, 0.0, 200.0)
+}
+
+/// set_audio_settings
+pub fn audio_einstellungen(config: &AudioSettingsConfig) -> Ergebnis {
+    optionaler_text(
+        "Eingabegeraet",
+        config.input_device_id.as_deref(),
+        MAX_EINSTELLUNG,
+    )?;
+    optionaler_text(
+        "Ausgabegeraet",
+        config.output_device_id.as_deref(),
+        MAX_EINSTELLUNG,
+    )?;
+    optionaler_text("PTT-Taste", config.ptt_key.as_deref(), MAX_EINSTELLUNG)?;
+    text("Sprach-Modus", &config.voice_mode, MAX_EINSTELLUNG)?;
+    text("Preset", &config.preset, MAX_EINSTELLUNG)?;
+    text(
+        "Rauschunterdrueckung",
+        &config.noise_suppression,
+        MAX_EINSTELLUNG,
+    )?;
+    text(
+        "Codec-Anwendung",
+        &config.codec.application,
+        MAX_EINSTELLUNG,
+    )?;
+    text("Codec-Kanaele", &config.codec.channels, MAX_EINSTELLUNG)?;
+    float_bereich("VAD-Empfindlichkeit", config.vad_sensitivity, 0.0, 1.0)?;
+    float_bereich("Eingangslautstaerke", config.input_volume, 0.0, 200.0)?;
+    float_bereich("Ausgangslautstaerke", config.output_volume, 0.0, 200.0)?;
+    bereich("Abtastrate", config.codec.sample_rate, 8000, 48000)?;
+    bereich("Puffergroesse", config.codec.buffer_size, 16, 48000)?;
+    bereich("DTX-Keepalive", config.codec.dtx_keepalive_ms, 20, 5000)?;
+    bereich("Jitter-Puffer", config.jitter.min_buffer, 0, 2000)?;
+    bereich("Latenzbudget", config.jitter.latency_budget, 20, 2000)?;
+    // Das Maximum darf das Minimum nicht unterschreiten
+    bereich(
+        "Jitter-Puffer",
+        config.jitter.max_buffer,
+        config.jitter.min_buffer,
+        2000,
+    )
+}
+
+/// set_event_sound_settings
+///
+/// Gibt die Einstellungen mit kanonisiertem Sound-Pack-Pfad zurueck.
+pub fn event_sounds(mut config: EventSoundSettings) -> Ergebnis<EventSoundSettings> {
+    float_bereich("Event-Lautstaerke", config.volume, 0.0, 100.0)?;
+    float_bereich("Sprachabsenkung", config.max_ducking, 0.0, 100.0)?;
+    if let Some(dir) = config.sound_pack_dir.take() {
+        if !dir.trim().is_empty() {
+            let kanonisch = pfad("Sound-Pack", &dir, PfadArt::Verzeichnis)?;
+            config.sound_pack_dir = Some(kanonisch.to_string_lossy().into_owned());
+        }
+    }
+    Ok(config)
+}
+
+/// Prueft den Pfad einer neu anzulegenden Datei
+///
+/// Die Datei darf noch nicht existieren, ihr Verzeichnis schon. Zurueck
+/// kommt der Pfad mit kanonisiertem Verzeichnis.
+pub fn neue_datei(feld: &'static str, path: &str) -> Ergebnis<PathBuf> {
+    pflichttext(feld, path, MAX_PFAD)?;
+    let pfad = Path::new(path);
+    let name = pfad
+        .file_name()
+        .and_then(|n| n.to_str())
+        .ok_or_else(|| ValidationError::Pfad {
+            feld,
+            grund: "kein Dateiname angegeben".to_string(),
+        })?;
+    dateiname(name)?;
+    let verzeichnis = match pfad.parent() {
+        Some(p) if !p.as_os_str().is_empty() => p.to_string_lossy(),
+        _ => ".".into(),
+    };
+    let ziel = self::pfad(feld, &verzeichnis, PfadArt::Verzeichnis)?.join(name);
+    if ziel.exists() {
+        return Err(ValidationError::Pfad {
+            feld,
+            grund: "Datei existiert bereits".to_string(),
+        });
+    }
+    Ok(ziel)
+}
+
+/// start_voice_trace
+pub fn voice_trace(path: &str, max_mb: u32) -> Ergebnis<PathBuf> {
+    bereich("Trace-Groesse (MB)", max_mb, 1, MAX_TRACE_MB)?;
+    neue_datei("Trace-Datei", path)
+}
+
+/// download_file
+pub fn datei_download(file_id: &str, target_path: &str) -> Ergebnis<PathBuf> {
+    id("Datei-ID", file_id)?;
+    neue_datei("Zieldatei", target_path)
+}
+
+/// set_qos_settings
+pub fn qos(config: &QosSettings) -> Ergebnis {
+    if let Some(dscp) = config.voice_dscp {
+        bereich("Voice-DSCP", dscp, 0, DSCP_MAX)?;
+    }
+    if let Some(dscp) = config.control_dscp {
+        bereich("Control-DSCP", dscp, 0, DSCP_MAX)?;
+    }
+    Ok(())
+}
+
+/// set_ptt_config
+pub fn ptt(modus: &str, taste: Option<&str>) -> Ergebnis<PttMode> {
+    optionaler_text("PTT-Taste", taste, MAX_EINSTELLUNG)?;
+    crate::ptt::modus_aus_name(modus).ok_or_else(|| ValidationError::Ungueltig {
+        feld: "Sprach-Modus",
+        grund: format!("unbekannter Modus: {}", modus),
+    })
+}
+
+/// set_hardware_mute_settings
+pub fn hardware_stumm(config: &HardwareMuteSettings) -> Ergebnis {
+    bereich(
+        "Null-Signal-Dauer (ms)",
+        config.zero_signal_ms,
+        MIN_NULL_DAUER.as_millis() as u32,
+        MAX_NULL_DAUER.as_millis() as u32,
+    )
+}
+
+/// Prueft eine Benutzer-ID
+pub fn benutzer_id(wert: &str) -> Ergebnis<UserId> {
+    uuid("Benutzer-ID", wert).map(UserId)
+}
+
+/// invite_to_channel
+pub fn einladen(channel_id: &str, user_id: &str, message: Option<&str>) -> Ergebnis {
+    kanal_id(channel_id)?;
+    benutzer_id(user_id)?;
+    optionaler_text("Einladungsnachricht", message, MAX_EINLADUNGSNACHRICHT)
+}
+
+/// knock_channel
+pub fn anklopfen(channel_id: &str, message: Option<&str>) -> Ergebnis {
+    kanal_id(channel_id)?;
+    optionaler_text("Anklopfnachricht", message, MAX_EINLADUNGSNACHRICHT)
+}
+
+/// set_user_volume
+pub fn benutzer_gain(gain: f32) -> Ergebnis {
+    float_bereich("Benutzer-Lautstaerke", gain, 0.0, MAX_GAIN)
+}
+
+/// set_user_audio_preferences, import_settings
+pub fn benutzer_audio(config: &BenutzerAudioEinstellungen) -> Ergebnis {
+    bereich(
+        "Aufbewahrung (Tage)",
+        config.retention_days,
+        1,
+        MAX_AUFBEWAHRUNG_TAGE,
+    )?;
+    config
+        .users
+        .values()
+        .try_for_each(|audio| benutzer_gain(audio.gain))
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KANAL: &str = "6f1c1c3e-2b8a-4a57-9d2f-3c1e4f5a6b7c";
+
+    fn zu_lang(n: usize) -> String {
+        "a".repeat(n + 1)
+    }
+
+    // --- Grundbausteine ---
+
+    #[test]
+    fn text_grenzen() {
+        assert!(text("Feld", &"a".repeat(10), 10).is_ok());
+        assert_eq!(
+            text("Feld", &zu_lang(10), 10),
+            Err(ValidationError::ZuLang {
+                feld: "Feld",
+                max: 10,
+                ist: 11
+            })
+        );
+        assert!(text("Feld", "Zeile\nZeile\t", 100).is_ok());
+        assert!(text("Feld", "null\0byte", 100).is_err());
+    }
+
+    #[test]
+    fn pflichttext_lehnt_leerzeichen_ab() {
+        assert_eq!(
+            pflichttext("Name", "   ", 10),
+            Err(ValidationError::Leer { feld: "Name" })
+        );
+    }
+
+    #[test]
+    fn fehlermeldungen_sind_einheitlich() {
+        let e = ValidationError::ZuGross {
+            feld: "Datei",
+            max: 10,
+            ist: 20,
+        };
+        assert_eq!(
+            String::from(e),
+            "Datei ist zu gross (20 von maximal 10 Bytes)"
+        );
+    }
+
+    #[test]
+    fn adresse_pruefen() {
+        assert!(adresse("voice.example.org").is_ok());
+        assert!(adresse("192.168.1.10").is_ok());
+        assert!(adresse("[::1]").is_ok());
+        assert!(adresse("").is_err());
+        assert!(adresse("host name").is_err());
+        assert!(adresse("host/pfad").is_err());
+        assert!(adresse(&zu_lang(MAX_ADRESSE)).is_err());
+    }
+
+    #[test]
+    fn port_null_abgelehnt() {
+        assert!(port(0).is_err());
+        assert!(port(9001).is_ok());
+    }
+
+    #[test]
+    fn kanal_id_pruefen() {
+        assert!(kanal_id(KANAL).is_ok());
+        assert!(kanal_id("kein-uuid").is_err());
+        assert!(kanal_id(&zu_lang(MAX_ID)).is_err());
+    }
+
+    #[test]
+    fn namen_ids_pruefen() {
+        let (benutzer, kanaele) = namen_aufloesen(&[KANAL.into()], &[KANAL.into()]).unwrap();
+        assert_eq!(benutzer[0].inner(), kanaele[0].inner());
+        assert!(namen_aufloesen(&["kein-uuid".into()], &[]).is_err());
+        assert!(namen_aufloesen(&vec![KANAL.to_string(); MAX_NAMEN_IDS + 1], &[]).is_err());
+    }
+
+    #[test]
+    fn einladung_pruefen() {
+        assert!(einladen(KANAL, KANAL, Some("Komm rein")).is_ok());
+        assert!(einladen(KANAL, "kein-uuid", None).is_err());
+        assert!(einladen(KANAL, KANAL, Some(&zu_lang(MAX_EINLADUNGSNACHRICHT))).is_err());
+        assert!(anklopfen(KANAL, None).is_ok());
+        assert!(anklopfen(KANAL, Some(&zu_lang(MAX_EINLADUNGSNACHRICHT))).is_err());
+    }
+
+    #[test]
+    fn dateiname_ohne_pfad() {
+        assert!(dateiname("bericht.pdf").is_ok());
+        assert!(dateiname("../etc/passwd").is_err());
+        assert!(dateiname("C:\\boot.ini").is_err());
+        assert!(dateiname("..").is_err());
+    }
+
+    #[test]
+    fn mime_typ_format() {
+        assert!(mime_typ("image/png").is_ok());
+        assert!(mime_typ("image").is_err());
+        assert!(mime_typ("/png").is_err());
+        assert!(mime_typ("text/plain; charset=utf-8").is_err());
+    }
+
+    #[test]
+    fn float_nan_abgelehnt() {
+        assert!(float_bereich("Wert", f32::NAN, 0.0, 1.0).is_err());
+        assert!(float_bereich("Wert", 1.5, 0.0, 1.0).is_err());
+        assert!(float_bereich("Wert", 0.5, 0.0, 1.0).is_ok());
+    }
+
+    #[test]
+    fn pfad_muss_existieren_und_art_passen() {
+        let dir = std::env::temp_dir();
+        let verzeichnis = dir.to_str().unwrap();
+        assert!(pfad("Pfad", verzeichnis, PfadArt::Verzeichnis).is_ok());
+        assert!(pfad("Pfad", verzeichnis, PfadArt::Datei).is_err());
+        assert!(pfad("Pfad", "/gibt/es/sicher/nicht", PfadArt::Verzeichnis).is_err());
+    }
+
+    #[test]
+    fn pfad_lehnt_netzwerkpfade_ab() {
+        for unc in ["\\\\server\\freigabe", "//server/freigabe"] {
+            assert!(matches!(
+                pfad("Pfad", unc, PfadArt::Verzeichnis),
+                Err(ValidationError::Pfad { .. })
+            ));
+        }
+    }
+
+    // --- Je Command-Kategorie: zu grosse Eingaben vor jedem Netzwerkzugriff ---
+
+    #[test]
+    fn verbindung_zu_langer_benutzername() {
+        assert!(verbindung("localhost", 9001, "alice", None).is_ok());
+        assert!(verbindung("localhost", 9001, &zu_lang(MAX_BENUTZERNAME), None).is_err());
+        assert!(verbindung("localhost", 9001, "alice", Some(&zu_lang(MAX_PASSWORT))).is_err());
+    }
+
+    #[test]
+    fn kanal_zu_langer_name_und_beschreibung() {
+        assert!(kanal_erstellen("Lobby", None, None, Some(10), Some(KANAL)).is_ok());
+        assert!(kanal_erstellen(&zu_lang(MAX_KANALNAME), None, None, None, None).is_err());
+        assert!(kanal_bearbeiten(
+            KANAL,
+            None,
+            Some(&zu_lang(MAX_KANALBESCHREIBUNG)),
+            None,
+            None
+        )
+        .is_err());
+        assert!(kanal_erstellen("Lobby", None, None, Some(MAX_KANAL_CLIENTS + 1), None).is_err());
+    }
+
+    #[test]
+    fn chat_zu_lange_nachricht() {
+        assert!(nachricht_senden(KANAL, "Hallo", None, MAX_NACHRICHT).is_ok());
+        assert!(nachricht_senden(KANAL, &zu_lang(MAX_NACHRICHT), None, MAX_NACHRICHT).is_err());
+        assert!(nachricht_bearbeiten("m1", &zu_lang(MAX_NACHRICHT), MAX_NACHRICHT).is_err());
+        // Der Server darf eine kleinere Grenze melden
+        assert!(nachricht_senden(KANAL, "Hallo", None, 3).is_err());
+        assert!(nachrichten_verlauf(KANAL, None, Some(MAX_HISTORY_LIMIT + 1)).is_err());
+        assert!(nachrichten_suchen(KANAL, "50% _x_", None, None, Some(20)).is_ok());
+        assert!(nachrichten_suchen(KANAL, "  ", None, None, None).is_err());
+        assert!(nachrichten_suchen(KANAL, &zu_lang(MAX_SUCHTEXT), None, None, None).is_err());
+        assert!(nachrichten_suchen(KANAL, "x", None, Some("m 1"), None).is_err());
+    }
+
+    #[test]
+    fn chat_grenze_zaehlt_zeichen() {
+        let umlaute = "\u{e4}".repeat(MAX_NACHRICHT);
+        assert!(nachricht_senden(KANAL, &umlaute, None, MAX_NACHRICHT).is_ok());
+        assert_eq!(
+            nachricht_bearbeiten("m1", &format!("{umlaute}x"), MAX_NACHRICHT),
+            Err(ValidationError::ZuLang {
+                feld: "Nachricht",
+                max: MAX_NACHRICHT,
+                ist: MAX_NACHRICHT + 1,
+            })
+        );
+    }
+
+    #[test]
+    fn mehrzeilige_eingaben() {
+        let text = zeilenumbrueche("eins\r\nzwei\rdrei\n".to_string());
+        assert_eq!(text, "eins\nzwei\ndrei\n");
+        assert!(nachricht_senden(KANAL, &text, None, MAX_NACHRICHT).is_ok());
+        assert!(nachricht_senden(KANAL, "eins\r\nzwei", None, MAX_NACHRICHT).is_err());
+        assert!(entwurf(KANAL, "").is_ok());
+        assert!(entwurf(KANAL, &zu_lang(MAX_ENTWURF_ZEICHEN)).is_err());
+    }
+
+    #[test]
+    fn datei_upload_zu_gross() {
+        assert!(datei_upload(KANAL, "a.txt", "text/plain", b"abc").is_ok());
+        let gross = vec![0u8; MAX_UPLOAD_BYTES + 1];
+        assert!(matches!(
+            datei_upload(KANAL, "a.txt", "text/plain", &gross),
+            Err(ValidationError::ZuGross { .. })
+        ));
+    }
+
+    #[test]
+    fn account_zu_lange_werte() {
+        assert!(nickname(&zu_lang(MAX_BENUTZERNAME)).is_err());
+        assert!(passwort_aendern("alt", &zu_lang(MAX_PASSWORT)).is_err());
+        assert!(abwesenheit(Some(&zu_lang(MAX_ABWESENHEIT))).is_err());
+    }
+
+    #[test]
+    fn plugin_id_zu_lang() {
+        assert!(uuid("Plugin-ID", &zu_lang(MAX_ID)).is_err());
+        assert!(uuid("Plugin-ID", KANAL).is_ok());
+    }
+
+    #[test]
+    fn audio_einstellungen_zu_lange_geraete_id() {
+        let mut config = AudioConfig {
+            input_device_id: Some(zu_lang(MAX_EINSTELLUNG)),
+            output_device_id: None,
+            input_volume: 100.0,
+            output_volume: 100.0,
+            noise_suppression: true,
+            echo_cancellation: false,
+        };
+        assert!(audio_config(&config).is_err());
+        config.input_device_id = None;
+        assert!(audio_config(&config).is_ok());
+    }
+
+    #[test]
+    fn jitter_modi_und_latenzbudget() {
+        use crate::commands::{default_audio_settings, JitterConfig};
+
+        let mut config = default_audio_settings();
+        assert!(audio_einstellungen(&config).is_ok());
+        for modus in ["low_latency", "stability"] {
+            config.jitter = JitterConfig::modus(modus).unwrap();
+            assert!(audio_einstellungen(&config).is_ok(), "{modus}");
+        }
+        assert!(JitterConfig::modus("turbo").is_none());
+        config.jitter.latency_budget = 10;
+        assert!(audio_einstellungen(&config).is_err());
+    }
+
+    #[test]
+    fn qos_dscp_bereich() {
+        let mut config = QosSettings::default();
+        assert!(qos(&config).is_ok());
+        config.control_dscp = Some(26);
+        assert!(qos(&config).is_ok());
+        config.voice_dscp = Some(64);
+        assert!(qos(&config).is_err());
+        config.voice_dscp = None;
+        assert!(qos(&config).is_ok());
+    }
+
+    #[test]
+    fn ptt_modus_und_taste() {
+        assert_eq!(ptt("ptt_hold", Some("F")).unwrap(), PttMode::Hold);
+        assert_eq!(ptt("vad", None).unwrap(), PttMode::VoiceActivation);
+        assert!(ptt("immer", None).is_err());
+        assert!(ptt("ptt_toggle", Some(&"x".repeat(MAX_EINSTELLUNG + 1))).is_err());
+    }
+
+    #[test]
+    fn hardware_stumm_dauer_bereich() {
+        let mut config = HardwareMuteSettings::default();
+        assert!(hardware_stumm(&config).is_ok());
+        config.zero_signal_ms = 499;
+        assert!(hardware_stumm(&config).is_err());
+        config.zero_signal_ms = 60_000;
+        assert!(hardware_stumm(&config).is_ok());
+        config.zero_signal_ms = 60_001;
+        assert!(hardware_stumm(&config).is_err());
+    }
+
+    #[test]
+    fn voice_trace_pfad_und_groesse() {
+        let verzeichnis = std::env::temp_dir();
+        let neu = verzeichnis.join("speakeasy-validation-trace.ndjson");
+        let neu = neu.to_str().unwrap();
+        assert!(voice_trace(neu, 16).is_ok());
+        assert!(voice_trace(neu, 0).is_err());
+        assert!(voice_trace(neu, MAX_TRACE_MB + 1).is_err());
+        // Vorhandene Dateien werden nicht ueberschrieben
+        assert!(voice_trace(verzeichnis.to_str().unwrap(), 16).is_err());
+        assert!(voice_trace("/gibt/es/sicher/nicht/trace.ndjson", 16).is_err());
+    }
+
+    #[test]
+    fn download_ziel_muss_neu_sein() {
+        let verzeichnis = std::env::temp_dir();
+        let neu = verzeichnis.join("speakeasy-validation-download.bin");
+        let neu = neu.to_str().unwrap();
+        let id = "00000000-0000-0000-0000-000000000001";
+        assert!(datei_download(id, neu).is_ok());
+        assert!(datei_download("", neu).is_err());
+        assert!(datei_download(id, verzeichnis.to_str().unwrap()).is_err());
+    }
+
+    #[test]
+    fn benutzer_audio_grenzen() {
+        let mut config = BenutzerAudioEinstellungen::default();
+        assert!(benutzer_audio(&config).is_ok());
+        config.users.insert(
+            benutzer_id(KANAL).unwrap(),
+            crate::benutzer_audio::BenutzerAudio {
+                gain: 2.5,
+                muted: false,
+                last_seen: 0,
+            },
+        );
+        assert!(benutzer_audio(&config).is_err());
+        config.users.clear();
+        config.retention_days = 0;
+        assert!(benutzer_audio(&config).is_err());
+        assert!(benutzer_gain(f32::NAN).is_err());
+        assert!(benutzer_id("kein-uuid").is_err());
+    }
+
+    #[test]
+    fn event_sounds_ungueltiger_pack_pfad() {
+        let config = EventSoundSettings {
+            sound_pack_dir: Some("//server/sounds".to_string()),
+            ..Default::default()
+        };
+        assert!(event_sounds(config).is_err());
+    }
+}
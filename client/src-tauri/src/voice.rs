@@ -9,44 +9,482 @@
 //!     -> Ring-Buffer (lock-free, ringbuf)
 //!     -> Processing Thread: Frames sammeln (20ms = 960 Samples bei 48kHz)
 //!     -> DSP Pipeline: NoiseGate -> NoiseSuppression -> AGC
-//!     -> Opus Encode: PCM f32 -> Opus bytes
+//!     -> Encode (Opus, oder PCMU wenn ausgehandelt): PCM f32 -> bytes
 //!     -> VoicePacket: Header (sequence++, timestamp, ssrc) + Opus Payload
 //!     -> UDP Socket send_to(server_addr)
 //! ```
 //!
+//! ## Anmeldung am Server (Hello)
+//! Der Server kennt den UDP-Endpunkt des Clients erst aus einem Hello
+//! (hinter NAT weicht der Quellport von allem ab, was per TCP bekannt ist).
+//! Der Empfangs-Loop sendet deshalb alle [`HELLO_INTERVALL`] ein mit der
+//! Nonce aus `VoiceReady` authentifiziertes Hello, bis das erste Paket vom
+//! Server eintrifft (Bestaetigung oder weitergeleitete Sprache).
+//!
 //! ## Empfangs-Pipeline (Server -> Playback)
 //! ```text
 //! UDP Socket recv_from()
 //!     -> VoicePacket parse (Header + Payload)
-//!     -> Opus Decode: Opus bytes -> PCM f32
-//!     -> Volume Control
+//!     -> Jitter-Puffer je SSRC, im 20ms-Takt in Sequenz-Reihenfolge entnommen
+//!        (fehlende Frames per PLC verdeckt)
+//!     -> Decode je SSRC (Codec aus den Header-Flags): bytes -> PCM f32
+//!     -> Volume Control (gespeicherte Lautstaerke/Mute des Benutzers hinter der SSRC)
 //!     -> Playback Ring-Buffer
 //!     -> cpal Playback Callback liest aus Ring-Buffer
 //! ```
+//!
+//! Event-Sounds werden ueber einen zweiten Ring-Buffer (Effekt-Quelle)
+//! im Playback-Callback zur Sprache gemischt.
+//!
+//! ## Codec-Fehler
+//! Encoder und Decoder werden in `start()` erstellt, bevor irgendetwas
+//! gestartet wird; schlaegt das fehl (z.B. fehlendes libopus), meldet
+//! `start()` [`VoiceStartFehler::CodecNichtVerfuegbar`]. Ohne Opus fragt der
+//! Client PCMU an, sofern der Server den Fallback erlaubt. Kann ein einzelner
+//! eingehender Stream nicht dekodiert werden, wird nur dieser uebersprungen
+//! und ein [`VoiceEreignis`] gemeldet.
+//!
+//! ## Hardware-Stummschaltung
+//! Der Sende-Loop prueft das rohe Capture-Signal auf eine Stummschaltung
+//! per Headset-Taste bzw. im Betriebssystem ([`HardwareStummErkennung`]),
+//! auch waehrend der Client logisch gemutet ist. Jeder Wechsel wird als
+//! [`VoiceEreignis::HardwareMuteDetected`] gemeldet.
+//!
+//! ## Hintergrundgeraeusche
+//! Gesendete Frames laufen zusaetzlich durch die
+//! [`StoergeraeuschErkennung`]. Sendet der Benutzer laenger ueberwiegend
+//! Luefter, Musik o.ae., meldet der Loop
+//! [`VoiceEreignis::BackgroundNoiseWarning`] (hoechstens alle paar Minuten);
+//! die Oberflaeche bietet dann eine staerkere Rauschunterdrueckung an.
+//!
+//! ## Latenzbudget
+//! Der Jitter-Puffer waechst hoechstens bis zum Latenzbudget
+//! (`latency_budget`); was darueber hinaus ankommt, wird verworfen und per
+//! PLC verdeckt. Verlangt der gemessene Jitter dauerhaft mehr, meldet der
+//! Empfangs-Loop einmal [`VoiceEreignis::NetworkUnstable`]; die Oberflaeche
+//! schlaegt dann den Stabilitaetsmodus vor.
+//!
+//! ## Push-to-Talk
+//! Im PTT-Modus sendet der Loop nur bei freigegebener [`SendeFreigabe`] und
+//! ohne RMS-Gate. Endet das Senden mitten im Sprechen (Taste losgelassen,
+//! Mute), geht ein letztes Paket mit `SPEAKING_STOP` raus, damit die
+//! Sprech-Anzeige der anderen sofort erlischt.
+//!
+//! ## DTX
+//! In Sprechpausen sendet der Loop nicht jeden Frame: nach dem Paket mit
+//! `SPEAKING_STOP` folgt nur noch alle `dtx_keepalive` ein Silence-Paket,
+//! damit NAT-Bindungen offen bleiben ([`Dtx`]). Die Sequenznummer zaehlt nur
+//! gesendete Pakete, der Zeitstempel laeuft dagegen mit jedem Frame weiter,
+//! damit der Jitter-Puffer des Empfaengers die Luecke nicht als Verlust
+//! wertet. Mit Sprachbeginn geht sofort wieder jeder Frame raus.
+//!
+//! ## Steuerung
+//! Laufen, Mute, Deaf, Nur-Zuhoeren und Notfall-Sperre bilden einen
+//! [`SteuerZustand`], den der Sende-Loop pro Durchlauf und der Empfangs-Loop
+//! pro Paket als Ganzes lesen. `stop()` setzt den Zustand auf gestoppt und
+//! wartet dann mit Frist auf Empfangs-Task und Audio-Thread; der Audio-Thread
+//! sieht den Stopp spaetestens nach einem Frame.
+//!
+//! ## E2E-Verschluesselung
+//! Im Modus `e2e` ([`VoiceClient::set_verschluesselung`]) verschluesselt der
+//! Sende-Loop jedes Audio-Paket mit dem aktuellen Gruppenschluessel und
+//! sendet ohne Schluessel gar nicht; der Empfangs-Loop entschluesselt vor dem
+//! Jitter-Puffer und verwirft, was er nicht entschluesseln kann (siehe
+//! [`voice_krypto`](crate::voice_krypto)).
+//!
+//! ## Echo-Unterdrueckung
+//! Mit [`VoiceClient::set_echo_unterdrueckung`] speist der Empfangs-Loop jeden
+//! gemischten Ausgabe-Frame als Referenz in einen begrenzten Puffer; die
+//! Echo Cancellation am Ende der Sende-Pipeline liest je Mikrofon-Frame die
+//! zeitgleiche Referenz und zieht deren Echo ab. Effekt-Sounds fliessen nicht
+//! in die Referenz ein.
+//!
+//! ## Debugzustand
+//! [`VoiceClient::debug_zustand`] liest Steuer-Zustand, Konfiguration und
+//! Zaehler fuer die Fehlersuche, ohne Sende- oder Empfangs-Loop aufzuhalten
+//! (siehe [`voice_debug`](crate::voice_debug)).
 
 use ringbuf::traits::{Consumer, Producer};
-use speakeasy_audio::codec::{OpusDecoder, OpusEncoder};
-use speakeasy_audio::pipeline::build_minimal_capture_pipeline;
+use serde::Serialize;
+use speakeasy_audio::codec::{
+    frame_angleichen, BitrateVorgabe, CodecStatistik, CodecZaehler, SprachDecoder, SprachEncoder,
+};
+use speakeasy_audio::dsp::echo_cancel::{
+    echo_referenz, EchoCancelConfig, EchoCanceller, EchoReferenz,
+};
+use speakeasy_audio::hardware_stumm::{HardwareStummErkennung, STANDARD_NULL_DAUER};
+use speakeasy_audio::pipeline::{build_minimal_capture_pipeline, AudioPipeline};
+use speakeasy_audio::stoergeraeusch::{StoergeraeuschErkennung, StoergeraeuschKonfig};
 use speakeasy_audio::volume::VolumeController;
-use speakeasy_protocol::codec::{AudioPreset, OpusConfig};
-use speakeasy_protocol::voice::{VoiceFlags, VoicePacket, VoicePacketHeader};
+use speakeasy_audio::{
+    DuckingRegler, EffektProducer, EmpfangsStrom, MischerKonfig, UnterlaufZaehler,
+    WiedergabeMischer,
+};
+use speakeasy_core::types::UserId;
+use speakeasy_protocol::codec::{
+    AudioPreset, CodecNegotiationRequest, CodecNegotiationResponse, NegotiationStatus, OpusConfig,
+};
+use speakeasy_protocol::qos::{self, QosStatus, SockRef};
+use speakeasy_protocol::socket_statistik::{self, DropErkennung, ABHILFE_HINWEIS};
+use speakeasy_protocol::udp_fehler::{self, UdpFehlerArt};
+use speakeasy_protocol::voice::{
+    AudioCodec, HelloPaket, PacketType, VoiceFlags, VoicePacket, VoicePacketHeader,
+};
+use std::collections::HashMap;
 use std::net::SocketAddr;
 use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 use tokio::net::UdpSocket;
+use tokio::sync::watch;
 use tracing::{debug, error, info, trace, warn};
 
+use crate::benutzer_audio::{self, BenutzerPegel};
+use crate::ptt::SendeFreigabe;
+use crate::voice_debug::{JitterDebug, PipelineDebug, PipelineZaehler, VoiceSitzung};
+use crate::voice_jitter::{self, Abspielen, EmpfangsPuffer, JitterEinstellung};
+use crate::voice_krypto::VoiceSchluessel;
+use crate::voice_stats::{self, PaketZaehler, VerbindungsStatistik};
+use crate::voice_steuerung::{self, SteuerZustand, Steuerung, STOP_FRIST};
+use crate::voice_trace::{Richtung, VoiceTrace};
+
 /// Frame-Groesse: 20ms bei 48kHz Mono = 960 Samples
 const FRAME_SIZE: usize = 960;
 /// Abtastrate
 const SAMPLE_RATE: u32 = 48000;
 /// Maximale UDP-Paketgroesse
 const UDP_BUFFER_SIZE: usize = 1400;
+/// Maximale Anzahl nicht abgeholter Ereignisse
+const MAX_EREIGNISSE: usize = 64;
+/// Groesse des Referenz-Puffers der Echo-Unterdrueckung (200ms)
+const ECHO_REFERENZ_KAPAZITAET: usize = 9600;
+/// Abstand zwischen zwei Abfragen der Kernel-Zaehler des UDP-Sockets
+const SOCKET_PRUEF_INTERVALL: std::time::Duration = std::time::Duration::from_secs(5);
+
+/// Playback-Stream und Producer (Sprache + Effekte) aus dem Audio-Thread
+type PlaybackStreams = (
+    speakeasy_audio::playback::PlaybackStream,
+    speakeasy_audio::PlaybackProducer,
+    EffektProducer,
+);
+
+/// Capture-Stream und Consumer aus dem Audio-Thread
+type CaptureStreams = (
+    speakeasy_audio::capture::CaptureStream,
+    speakeasy_audio::CaptureConsumer,
+);
+
+/// Pause des Audio-Threads ohne Capture (Nur-Zuhoeren)
+const NUR_HOEREN_PAUSE: std::time::Duration = std::time::Duration::from_millis(20);
+
+/// Abstand der Silence-Pakete in Sprechpausen (NAT-Keepalive)
+pub const STANDARD_DTX_KEEPALIVE: std::time::Duration = std::time::Duration::from_millis(400);
+
+/// Abstand der Hello-Wiederholungen bis zum ersten Paket vom Server
+pub const HELLO_INTERVALL: std::time::Duration = std::time::Duration::from_millis(200);
+
+/// Hellos, nach denen der Client aufgibt (10 s ohne Antwort)
+const HELLO_MAX_VERSUCHE: u32 = 50;
+
+/// Audio-Preset der Voice-Pipeline, begrenzt auch die adaptive Bitrate
+const STANDARD_PRESET: AudioPreset = AudioPreset::Balanced;
+
+/// Opus-Konfiguration der Voice-Pipeline
+pub fn standard_opus_config() -> OpusConfig {
+    STANDARD_PRESET.config()
+}
+
+/// Codec-Aushandlung fuer `VoiceInit` mit dem Standard-Preset
+pub fn standard_codec_anfrage() -> CodecNegotiationRequest {
+    CodecNegotiationRequest::fuer_preset(STANDARD_PRESET)
+}
+
+// ---------------------------------------------------------------------------
+// Fehler und Ereignisse
+// ---------------------------------------------------------------------------
+
+/// Fehler beim Start der Voice-Pipeline
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VoiceStartFehler {
+    /// Die Pipeline laeuft bereits
+    LaeuftBereits,
+    /// Encoder oder Decoder des ausgehandelten Codecs nicht erstellbar
+    CodecNichtVerfuegbar { codec: AudioCodec, grund: String },
+    /// UDP-Socket konnte nicht geoeffnet werden
+    Netzwerk(String),
+    /// Audio-Geraete oder Audio-Thread konnten nicht gestartet werden
+    Audio(String),
+}
+
+impl VoiceStartFehler {
+    /// Meldung fuer die Oberflaeche mit Hinweis, was der Benutzer tun kann
+    pub fn benutzer_meldung(&self) -> String {
+        match self {
+            VoiceStartFehler::CodecNichtVerfuegbar {
+                codec: AudioCodec::Opus,
+                ..
+            } => "Der Sprachcodec Opus ist auf diesem System nicht verfuegbar \
+                  (libopus fehlt oder ist inkompatibel). Bitte libopus installieren \
+                  oder aktualisieren, oder den Server-Betreiber bitten, den \
+                  PCM-Fallback zu erlauben."
+                .to_string(),
+            VoiceStartFehler::CodecNichtVerfuegbar { codec, grund } => format!(
+                "Der Sprachcodec {} konnte nicht gestartet werden: {}",
+                codec.name(),
+                grund
+            ),
+            VoiceStartFehler::Netzwerk(grund) => format!(
+                "Voice-Verbindung konnte nicht aufgebaut werden ({}). \
+                 Bitte Netzwerk und Firewall pruefen.",
+                grund
+            ),
+            VoiceStartFehler::Audio(grund) => format!(
+                "Audio-Geraete konnten nicht geoeffnet werden ({}). \
+                 Bitte Mikrofon und Lautsprecher in den Einstellungen pruefen.",
+                grund
+            ),
+            VoiceStartFehler::LaeuftBereits => self.to_string(),
+        }
+    }
+}
+
+impl std::fmt::Display for VoiceStartFehler {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            VoiceStartFehler::LaeuftBereits => write!(f, "Voice-Pipeline laeuft bereits"),
+            VoiceStartFehler::CodecNichtVerfuegbar { codec, grund } => {
+                write!(f, "Codec {} nicht verfuegbar: {}", codec.name(), grund)
+            }
+            VoiceStartFehler::Netzwerk(grund) => write!(f, "Netzwerkfehler: {}", grund),
+            VoiceStartFehler::Audio(grund) => write!(f, "Audiofehler: {}", grund),
+        }
+    }
+}
+
+impl std::error::Error for VoiceStartFehler {}
+
+impl From<VoiceStartFehler> for String {
+    fn from(e: VoiceStartFehler) -> Self {
+        e.benutzer_meldung()
+    }
+}
+
+/// Ereignis der laufenden Pipeline, das die Oberflaeche abholt
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "typ", rename_all = "snake_case")]
+pub enum VoiceEreignis {
+    /// Fuer diese SSRC gibt es keinen Decoder; der Stream wird uebersprungen
+    StreamUebersprungen {
+        ssrc: u32,
+        codec: String,
+        grund: String,
+    },
+    /// Das Mikrofon ist per Hardware-Taste bzw. im System stumm (oder nicht mehr)
+    HardwareMuteDetected { muted: bool },
+    /// Es werden laenger ueberwiegend Hintergrundgeraeusche gesendet;
+    /// `score` ist deren Anteil (0.0 – 1.0)
+    BackgroundNoiseWarning { score: f32 },
+    /// Der Jitter verlangt mehr Puffer als das Latenzbudget (`budget_ms`) erlaubt
+    NetworkUnstable { budget_ms: u32 },
+}
+
+/// Meldet Wechsel des eigenen Sprech-Zustands (z.B. als Tauri-Event)
+pub type SprechMelder = Arc<dyn Fn(bool) + Send + Sync>;
+
+/// Eigener Sprech-Zustand samt Meldung an die Oberflaeche
+#[derive(Clone)]
+struct SprechZustand {
+    spricht: Arc<AtomicBool>,
+    melder: Option<SprechMelder>,
+}
+
+impl SprechZustand {
+    /// Setzt den Zustand und meldet nur echte Wechsel
+    fn setzen(&self, spricht: bool) {
+        if self.spricht.swap(spricht, Ordering::Relaxed) != spricht {
+            if let Some(melder) = &self.melder {
+                melder(spricht);
+            }
+        }
+    }
+}
+
+/// Decoder je eingehender SSRC
+///
+/// Jeder Sprecher bekommt einen eigenen Decoder fuer den Codec seiner Pakete,
+/// samt Verlust-Ausgleich ueber seine Sequenznummern. Schlaegt die
+/// Erstellung fehl, wird nur dieser Stream uebersprungen; das Ereignis wird
+/// einmal pro SSRC und Codec gemeldet.
+struct StreamDekoder {
+    opus_config: OpusConfig,
+    /// `None` = Erstellung fehlgeschlagen, Stream wird uebersprungen
+    streams: HashMap<u32, (AudioCodec, Option<EmpfangsStrom>)>,
+    ereignisse: Arc<Mutex<Vec<VoiceEreignis>>>,
+    /// Codec-Fehler aller Streams
+    zaehler: CodecZaehler,
+}
+
+impl StreamDekoder {
+    fn neu(
+        opus_config: OpusConfig,
+        ereignisse: Arc<Mutex<Vec<VoiceEreignis>>>,
+        zaehler: CodecZaehler,
+    ) -> Self {
+        Self {
+            opus_config,
+            streams: HashMap::new(),
+            ereignisse,
+            zaehler,
+        }
+    }
+
+    /// Decoder fuer ein Paket; `None` wenn der Stream uebersprungen wird
+    fn fuer(&mut self, ssrc: u32, codec: AudioCodec) -> Option<&mut EmpfangsStrom> {
+        let vorhanden = self.streams.get(&ssrc).is_some_and(|(c, _)| *c == codec);
+        if !vorhanden {
+            let decoder = match SprachDecoder::new(codec, &self.opus_config) {
+                Ok(decoder) => Some(EmpfangsStrom::neu(decoder).mit_zaehler(self.zaehler.clone())),
+                Err(e) => {
+                    self.zaehler.fehler_erfassen(&e);
+                    warn!(
+                        ssrc,
+                        codec = codec.name(),
+                        "Decoder konnte nicht erstellt werden, Stream wird uebersprungen: {}",
+                        e
+                    );
+                    ereignis_melden(
+                        &self.ereignisse,
+                        VoiceEreignis::StreamUebersprungen {
+                            ssrc,
+                            codec: codec.name().to_string(),
+                            grund: e.to_string(),
+                        },
+                    );
+                    None
+                }
+            };
+            self.streams.insert(ssrc, (codec, decoder));
+        }
+        self.streams.get_mut(&ssrc).and_then(|(_, d)| d.as_mut())
+    }
+
+    /// Silence-Paket eines bekannten Streams: Sequenz fortschreiben
+    fn stille(&mut self, ssrc: u32, sequenz: u32) {
+        if let Some((_, Some(strom))) = self.streams.get_mut(&ssrc) {
+            strom.stille(sequenz);
+        }
+    }
+
+    /// Paket, das der Mischer ohnehin verwerfen wuerde: nicht dekodieren,
+    /// aber die Sequenz fortschreiben (kein Verlust)
+    fn ueberspringen(&mut self, ssrc: u32, sequenz: u32) {
+        if let Some((_, Some(strom))) = self.streams.get_mut(&ssrc) {
+            strom.ueberspringen(sequenz);
+        }
+    }
+
+    /// Fehlender Frame eines bekannten Streams: PLC
+    fn verdecken(&mut self, ssrc: u32) -> Option<Vec<f32>> {
+        let Some((_, Some(strom))) = self.streams.get_mut(&ssrc) else {
+            return None;
+        };
+        match strom.verdecken() {
+            Ok(pcm) if !pcm.is_empty() => Some(pcm),
+            Ok(_) => None,
+            Err(e) => {
+                trace!("PLC fehlgeschlagen: {}", e);
+                None
+            }
+        }
+    }
+}
+
+/// Lautstaerke pro Benutzer im Empfangspfad
+///
+/// Die gespeicherte Einstellung wird ab dem ersten Paket angewendet, dessen
+/// SSRC einem Benutzer zugeordnet ist, und neu bestimmt, sobald die SSRC
+/// einem anderen Benutzer gehoert. Nicht zugeordnete SSRCs bleiben
+/// unveraendert.
+struct EmpfangsMischer {
+    lautstaerke: VolumeController,
+    pegel: Arc<BenutzerPegel>,
+    /// Zuletzt erkannter Benutzer je SSRC
+    zugeordnet: HashMap<u32, UserId>,
+}
+
+impl EmpfangsMischer {
+    fn neu(pegel: Arc<BenutzerPegel>) -> Self {
+        Self {
+            lautstaerke: VolumeController::new(),
+            pegel,
+            zugeordnet: HashMap::new(),
+        }
+    }
+
+    /// Wendet den Pegel des Sprechers an; `false` = Benutzer stumm, verwerfen
+    fn anwenden(&mut self, ssrc: u32, samples: &mut [f32]) -> bool {
+        let Some((user_id, audio)) = self.pegel.fuer_ssrc(ssrc) else {
+            return true;
+        };
+        if self.zugeordnet.insert(ssrc, user_id) != Some(user_id) {
+            self.pegel.gesehen(&user_id, benutzer_audio::jetzt_unix());
+            if let Some(audio) = audio {
+                debug!(
+                    ssrc,
+                    user = %user_id.inner(),
+                    gain = audio.gain,
+                    muted = audio.muted,
+                    "Gespeicherte Benutzer-Lautstaerke angewendet"
+                );
+            }
+        }
+
+        let (gain, muted) = audio.map_or((1.0, false), |a| (a.gain, a.muted));
+        self.lautstaerke.set_user_volume(user_id, gain);
+        self.lautstaerke.set_user_muted(user_id, muted);
+        if muted {
+            return false;
+        }
+        self.lautstaerke.apply(user_id, samples);
+        true
+    }
+}
 
 // ---------------------------------------------------------------------------
 // VoiceClient
 // ---------------------------------------------------------------------------
 
+/// Ausstehende Anmeldung des UDP-Endpunkts beim Server (siehe Modul-Doku)
+struct HelloAnmeldung {
+    server_addr: SocketAddr,
+    ssrc: u32,
+    nonce: Vec<u8>,
+    versuch: u32,
+}
+
+impl HelloAnmeldung {
+    fn neu(server_addr: SocketAddr, ssrc: u32, nonce: Vec<u8>) -> Self {
+        Self {
+            server_addr,
+            ssrc,
+            nonce,
+            versuch: 0,
+        }
+    }
+
+    /// Sendet das naechste Hello; `false` wenn alle Versuche verbraucht sind
+    async fn senden(&mut self, socket: &UdpSocket) -> bool {
+        if self.versuch >= HELLO_MAX_VERSUCHE {
+            return false;
+        }
+        let hello = HelloPaket::neu(self.ssrc, self.versuch, &self.nonce);
+        if let Err(e) = socket.send_to(&hello.encode(), self.server_addr).await {
+            trace!("Hello nicht gesendet: {}", e);
+        }
+        self.versuch += 1;
+        true
+    }
+}
+
 /// Voice-Client: Verwaltet die gesamte Audio-Pipeline
 ///
 /// Lifecycle:
@@ -63,23 +501,67 @@ pub struct VoiceClient {
     ssrc: u32,
     /// Server UDP-Adresse
     server_addr: SocketAddr,
-    /// Laeuft die Pipeline?
-    running: Arc<AtomicBool>,
-    /// Ist das Mikrofon gemutet?
-    muted: Arc<AtomicBool>,
-    /// Ist der Ton deaktiviert (deaf)?
-    deafened: Arc<AtomicBool>,
+    /// Phase, Mute, Deaf, Nur-Zuhoeren und Notfall-Sperre als ein Zustand
+    steuerung: Steuerung,
     /// Spricht der Benutzer gerade?
     speaking: Arc<AtomicBool>,
     /// Sequenznummer fuer ausgehende Pakete
     sequence: Arc<AtomicU32>,
-    /// Shutdown-Signal fuer den Empfangs-Task
-    shutdown_tx: Option<tokio::sync::oneshot::Sender<()>>,
+    /// Vom Server vorgegebene Ziel-Bitrate in kbps (0 = Preset-Bitrate)
+    ziel_bitrate: Arc<AtomicU32>,
     /// Audio-Thread: haelt cpal-Streams am Leben und fuehrt den Sende-Loop aus
     /// (std::thread weil cpal::Stream !Send ist und nicht in Tokio-Tasks leben kann)
     audio_thread: Option<std::thread::JoinHandle<()>>,
     /// Empfangs-Task (async, in Tokio)
     recv_task: Option<tokio::task::JoinHandle<()>>,
+    /// Producer der Effekt-Quelle fuer Event-Sounds (nur solange die Pipeline laeuft)
+    effekte: Arc<Mutex<Option<EffektProducer>>>,
+    /// Maximale Absenkung der Sprache waehrend Event-Sounds
+    ducking: DuckingRegler,
+    /// Unterlaeufe des Playback-Ring-Buffers (ueber Neustarts hinweg)
+    playback_unterlauf: UnterlaufZaehler,
+    /// Codec-Fehler je Kategorie (ueber Neustarts hinweg)
+    codec_zaehler: CodecZaehler,
+    /// Gesendete und empfangene Voice-Pakete (ueber Neustarts hinweg)
+    pakete: PaketZaehler,
+    /// Lokale Adresse des aktuellen UDP-Sockets (`None` = nicht gestartet)
+    lokale_adresse: Option<SocketAddr>,
+    /// Paketverlust getrennt nach Uplink und Downlink
+    statistik: Arc<Mutex<VerbindungsStatistik>>,
+    /// DSCP-Wert fuer ausgehende Voice-Pakete (`None` = unmarkiert)
+    dscp: Option<u8>,
+    /// Ergebnis der DSCP-Markierung des aktuellen UDP-Sockets
+    qos: QosStatus,
+    /// Netzwerk-Debugmodus (Paket-Trace), geteilt mit dem AppState
+    trace: Arc<VoiceTrace>,
+    /// Opus-Konfiguration fuer Encoder und Decoder
+    opus_config: OpusConfig,
+    /// Preset, dessen Grenzen fuer die adaptive Bitrate gelten
+    preset: AudioPreset,
+    /// Groesse des Jitter-Puffers im Empfangspfad
+    jitter: JitterEinstellung,
+    /// Beim letzten Start ausgehandelter Codec
+    codec: AudioCodec,
+    /// Noch nicht abgeholte Ereignisse der Pipeline
+    ereignisse: Arc<Mutex<Vec<VoiceEreignis>>>,
+    /// Lautstaerke pro Benutzer, geteilt mit dem AppState
+    benutzer_pegel: Arc<BenutzerPegel>,
+    /// Null-Signal-Dauer der Hardware-Stumm-Erkennung (`None` = aus)
+    hardware_stumm: Option<std::time::Duration>,
+    /// Abstand der Silence-Pakete in Sprechpausen (`None` = DTX aus)
+    dtx_keepalive: Option<std::time::Duration>,
+    /// Echo-Unterdrueckung mit Referenz aus dem Playback (`None` = aus)
+    echo: Option<EchoCancelConfig>,
+    /// Push-to-Talk-Freigabe, geteilt mit dem AppState
+    ptt_freigabe: Arc<SendeFreigabe>,
+    /// Meldet Wechsel des Sprech-Zustands an die Oberflaeche
+    sprech_melder: Option<SprechMelder>,
+    /// Beim Voice-Init ausgehandelte Sitzung (nur fuer den Debugzustand)
+    sitzung: Option<VoiceSitzung>,
+    /// Nonce fuer das Hello aus `VoiceReady` (`None` = kein Hello senden)
+    hello_nonce: Option<Vec<u8>>,
+    /// Gruppenschluessel im E2E-Modus (`None` = Nutzdaten im Klartext)
+    verschluesselung: Option<Arc<VoiceSchluessel>>,
 }
 
 impl VoiceClient {
@@ -88,195 +570,456 @@ impl VoiceClient {
         Self {
             ssrc: 0,
             server_addr: SocketAddr::from(([0, 0, 0, 0], 0)),
-            running: Arc::new(AtomicBool::new(false)),
-            muted: Arc::new(AtomicBool::new(false)),
-            deafened: Arc::new(AtomicBool::new(false)),
+            steuerung: Steuerung::neu(),
             speaking: Arc::new(AtomicBool::new(false)),
             sequence: Arc::new(AtomicU32::new(0)),
-            shutdown_tx: None,
+            ziel_bitrate: Arc::new(AtomicU32::new(0)),
             audio_thread: None,
             recv_task: None,
+            effekte: Arc::new(Mutex::new(None)),
+            ducking: DuckingRegler::default(),
+            playback_unterlauf: UnterlaufZaehler::default(),
+            codec_zaehler: CodecZaehler::default(),
+            pakete: PaketZaehler::default(),
+            lokale_adresse: None,
+            statistik: Arc::new(Mutex::new(VerbindungsStatistik::new())),
+            dscp: Some(qos::DSCP_EF),
+            qos: QosStatus::Deaktiviert,
+            trace: Arc::new(VoiceTrace::new()),
+            opus_config: standard_opus_config(),
+            preset: STANDARD_PRESET,
+            jitter: JitterEinstellung::default(),
+            codec: AudioCodec::Opus,
+            ereignisse: Arc::new(Mutex::new(Vec::new())),
+            benutzer_pegel: Arc::new(BenutzerPegel::neu()),
+            hardware_stumm: Some(STANDARD_NULL_DAUER),
+            dtx_keepalive: Some(STANDARD_DTX_KEEPALIVE),
+            echo: None,
+            ptt_freigabe: Arc::new(SendeFreigabe::default()),
+            sprech_melder: None,
+            sitzung: None,
+            hello_nonce: None,
+            verschluesselung: None,
+        }
+    }
+
+    /// Codec fuer `VoiceInit`: Opus, oder PCMU wenn Opus mit `opus_config`
+    /// nicht nutzbar ist
+    pub fn bevorzugter_codec(opus_config: &OpusConfig) -> AudioCodec {
+        match Self::encoder_erstellen(AudioCodec::Opus, opus_config) {
+            Ok(_) => AudioCodec::Opus,
+            Err(e) => {
+                warn!("{}, frage PCM-Fallback an", e);
+                AudioCodec::Pcmu
+            }
         }
     }
 
-    /// Startet die Voice-Pipeline
+    /// Erstellt den Encoder und prueft, dass auch ein Decoder erstellbar ist
+    fn encoder_erstellen(
+        codec: AudioCodec,
+        opus_config: &OpusConfig,
+    ) -> Result<SprachEncoder, VoiceStartFehler> {
+        let fehler = |e: speakeasy_audio::AudioError| VoiceStartFehler::CodecNichtVerfuegbar {
+            codec,
+            grund: e.to_string(),
+        };
+        SprachDecoder::new(codec, opus_config).map_err(fehler)?;
+        SprachEncoder::new(codec, opus_config.clone()).map_err(fehler)
+    }
+
+    /// Startet die Voice-Pipeline mit dem ausgehandelten Codec
+    ///
+    /// Liefert der Server eine vereinbarte Opus-Konfiguration
+    /// (`ausgehandelt`), ersetzt sie die bisher gesetzte.
     ///
-    /// 1. UDP-Socket oeffnen (OS waehlt Port)
-    /// 2. Audio-Thread starten (haelt cpal-Streams + fuehrt Sende-Loop aus)
-    /// 3. Empfangs-Task starten (async, schreibt in Playback-Ring-Buffer)
-    pub async fn start(&mut self, server_addr: SocketAddr, ssrc: u32) -> Result<(), String> {
-        if self.running.load(Ordering::Relaxed) {
-            return Err("Voice-Pipeline laeuft bereits".to_string());
+    /// 1. Encoder erstellen (Fehler hier brechen ab, bevor etwas laeuft)
+    /// 2. UDP-Socket oeffnen (OS waehlt Port)
+    /// 3. Audio-Thread starten (haelt cpal-Streams + fuehrt Sende-Loop aus)
+    /// 4. Empfangs-Task starten (async, schreibt in Playback-Ring-Buffer)
+    ///
+    /// Schlaegt ein Schritt fehl, wird alles bereits Gestartete wieder
+    /// beendet, bevor der Fehler zurueckkommt.
+    pub async fn start(
+        &mut self,
+        server_addr: SocketAddr,
+        ssrc: u32,
+        codec: AudioCodec,
+        ausgehandelt: Option<&CodecNegotiationResponse>,
+    ) -> Result<(), VoiceStartFehler> {
+        if let Some(antwort) = ausgehandelt {
+            self.ausgehandelt_uebernehmen(antwort);
+        }
+
+        // Erst den Zustand umschalten, dann Threads starten: so sieht der
+        // Audio-Thread nie einen Lauf, der noch nicht begonnen hat
+        let Some(lauf) = self.steuerung.starten() else {
+            return Err(VoiceStartFehler::LaeuftBereits);
+        };
+
+        if let Err(e) = self.starten_fuer(lauf, server_addr, ssrc, codec).await {
+            self.steuerung.stoppen();
+            self.abbauen().await;
+            return Err(e);
         }
 
+        info!("Voice-Pipeline gestartet");
+        Ok(())
+    }
+
+    /// Startet Socket, Audio-Thread und Empfangs-Task fuer den Lauf `lauf`
+    async fn starten_fuer(
+        &mut self,
+        lauf: u64,
+        server_addr: SocketAddr,
+        ssrc: u32,
+        codec: AudioCodec,
+    ) -> Result<(), VoiceStartFehler> {
+        // 1. Codec pruefen, bevor der Kanalbeitritt als erfolgreich gilt
+        let encoder = Self::encoder_erstellen(codec, &self.opus_config)?;
+
+        self.codec = codec;
         self.ssrc = ssrc;
         self.server_addr = server_addr;
         self.sequence.store(0, Ordering::Relaxed);
+        // Der Server regelt jede Voice-Sitzung neu ab der Preset-Bitrate
+        self.ziel_bitrate.store(0, Ordering::Relaxed);
+        if let Ok(mut statistik) = self.statistik.lock() {
+            statistik.zuruecksetzen();
+        }
+        if let Ok(mut ereignisse) = self.ereignisse.lock() {
+            ereignisse.clear();
+        }
 
         info!(
             server = %server_addr,
             ssrc,
+            codec = codec.name(),
             "Starte Voice-Pipeline"
         );
 
-        // 1. UDP-Socket binden (Port 0 = OS waehlt)
-        let udp_socket = UdpSocket::bind("0.0.0.0:0")
-            .await
-            .map_err(|e| format!("UDP-Socket konnte nicht gebunden werden: {}", e))?;
+        // 2. UDP-Socket binden (Port 0 = OS waehlt)
+        let udp_socket = UdpSocket::bind("0.0.0.0:0").await.map_err(|e| {
+            VoiceStartFehler::Netzwerk(format!("UDP-Socket konnte nicht gebunden werden: {}", e))
+        })?;
 
-        let local_port = udp_socket
+        let lokale_adresse = udp_socket
             .local_addr()
-            .map_err(|e| e.to_string())?
-            .port();
-        info!(port = local_port, "UDP-Socket gebunden");
+            .map_err(|e| VoiceStartFehler::Netzwerk(e.to_string()))?;
+        info!(port = lokale_adresse.port(), "UDP-Socket gebunden");
+        self.lokale_adresse = Some(lokale_adresse);
+
+        self.qos = qos::markieren(SockRef::from(&udp_socket), self.dscp);
+        if let QosStatus::Abgelehnt { dscp, grund } = &self.qos {
+            warn!(dscp, grund = %grund, "DSCP-Markierung vom System abgelehnt");
+        }
+        if let Err(e) = udp_fehler::connreset_abschalten(SockRef::from(&udp_socket)) {
+            warn!(fehler = %e, "SIO_UDP_CONNRESET nicht gesetzt");
+        }
 
         let socket = Arc::new(udp_socket);
 
-        // Opus-Config fuer beide Loops
-        let opus_config = AudioPreset::Balanced.config();
+        // Decoder je SSRC fuer den Empfangs-Loop
+        let dekoder = StreamDekoder::neu(
+            self.opus_config.clone(),
+            Arc::clone(&self.ereignisse),
+            self.codec_zaehler.clone(),
+        );
 
         // Shared Flags
-        let running = Arc::clone(&self.running);
-        let muted = Arc::clone(&self.muted);
         let speaking = Arc::clone(&self.speaking);
         let sequence = Arc::clone(&self.sequence);
-        let deafened = Arc::clone(&self.deafened);
 
-        // 2. Audio-Thread starten
+        // 3. Audio-Thread starten
         // Dieser Thread:
-        //   a) Oeffnet cpal Capture + Playback Streams (diese sind !Send)
+        //   a) Oeffnet cpal Playback + Capture Streams (diese sind !Send),
+        //      Capture nur ausserhalb des Nur-Zuhoeren-Modus
         //   b) Fuehrt den Sende-Loop aus (blockierend)
-        //   c) Haelt die Streams am Leben bis running=false
+        //   c) Haelt die Streams am Leben, solange der Lauf gilt
         //   d) Gibt den PlaybackProducer via Channel an den Empfangs-Task
         let send_socket = Arc::clone(&socket);
-        let audio_running = Arc::clone(&running);
-        let audio_muted = Arc::clone(&muted);
-        let audio_speaking = Arc::clone(&speaking);
+        let audio_steuerung = self.steuerung.clone();
+        let mut audio_steuer_rx = self.steuerung.abonnieren();
+        let audio_sprechen = SprechZustand {
+            spricht: Arc::clone(&speaking),
+            melder: self.sprech_melder.clone(),
+        };
+        let audio_ptt = Arc::clone(&self.ptt_freigabe);
         let audio_sequence = Arc::clone(&sequence);
         let audio_server_addr = self.server_addr;
         let audio_ssrc = self.ssrc;
-        let audio_opus_config = opus_config.clone();
+        let audio_ducking = self.ducking.clone();
+        let audio_unterlauf = self.playback_unterlauf.clone();
+        let audio_codec_zaehler = self.codec_zaehler.clone();
+        let audio_pakete = self.pakete.clone();
+        let audio_trace = Arc::clone(&self.trace);
+        let audio_bitrate = BitrateVorgabe::neu(Arc::clone(&self.ziel_bitrate), self.preset);
+        let audio_hardware_stumm = self
+            .hardware_stumm
+            .map(|dauer| HardwareStummErkennung::neu(SAMPLE_RATE, dauer));
+        let audio_ereignisse = Arc::clone(&self.ereignisse);
+        let audio_dtx = Dtx::neu(self.dtx_keepalive, encoder.frame_size());
+        let audio_verschluesselung = self.verschluesselung.clone();
+        // Echo-Referenz: Empfangs-Loop schreibt, Sende-Pipeline liest
+        let (referenz, audio_echo) = match self.echo.clone() {
+            Some(config) => {
+                let (referenz, quelle) = echo_referenz(ECHO_REFERENZ_KAPAZITAET);
+                (
+                    Some(referenz),
+                    Some(EchoCanceller::new(config).mit_referenz(quelle)),
+                )
+            }
+            None => (None, None),
+        };
 
-        // Channel um den PlaybackProducer vom Audio-Thread zum Empfangs-Task zu uebergeben
+        // Channel um die Playback-Producer (Sprache + Effekte) vom Audio-Thread
+        // zum Empfangs-Task bzw. VoiceClient zu uebergeben; bei einem Fehler
+        // kommt stattdessen die Ursache
+        type PlaybackTeile = (speakeasy_audio::PlaybackProducer, EffektProducer);
         let (producer_tx, producer_rx) =
-            std::sync::mpsc::sync_channel::<speakeasy_audio::PlaybackProducer>(1);
+            std::sync::mpsc::sync_channel::<Result<PlaybackTeile, String>>(1);
 
         let audio_thread = std::thread::Builder::new()
             .name("voice-audio".to_string())
             .spawn(move || {
-                // Audio-Streams oeffnen (cpal::Stream lebt hier im Thread)
-                let streams = Self::start_audio_streams();
-                let (capture_consumer, playback_producer, _capture_stream, _playback_stream) =
-                    match streams {
-                        Ok((cs, cc, ps, pp)) => (cc, pp, cs, ps),
+                // Audio-Streams oeffnen (cpal::Stream lebt hier im Thread);
+                // als Nur-Zuhoerer bleibt das Mikrofon zu
+                let ergebnis =
+                    Self::playback_oeffnen(audio_ducking, audio_unterlauf).and_then(|playback| {
+                        let capture = if audio_steuer_rx.borrow().nur_hoeren {
+                            None
+                        } else {
+                            Some(Self::capture_oeffnen()?)
+                        };
+                        Ok((playback, capture))
+                    });
+                let ((_playback_stream, playback_producer, effekt_producer), mut capture) =
+                    match ergebnis {
+                        Ok(streams) => streams,
                         Err(e) => {
                             error!("Audio-Streams konnten nicht geoeffnet werden: {}", e);
-                            // Leeren Producer senden geht nicht, also Channel droppen
-                            drop(producer_tx);
+                            let _ = producer_tx.send(Err(e));
                             return;
                         }
                     };
 
                 // PlaybackProducer an den Empfangs-Task uebergeben
-                if producer_tx.send(playback_producer).is_err() {
+                if producer_tx
+                    .send(Ok((playback_producer, effekt_producer)))
+                    .is_err()
+                {
                     error!("Empfangs-Task hat PlaybackProducer nicht abgeholt");
                     return;
                 }
 
-                // Sende-Loop blockierend ausfuehren
-                // _capture_stream und _playback_stream bleiben im Scope am Leben
-                Self::sende_loop(
-                    capture_consumer,
-                    send_socket,
-                    audio_server_addr,
-                    audio_ssrc,
-                    audio_opus_config,
-                    audio_running,
-                    audio_muted,
-                    audio_speaking,
-                    audio_sequence,
-                );
+                // Sende-Loop blockierend ausfuehren; er kehrt auch beim Wechsel
+                // zu Nur-Zuhoeren zurueck, dann wird das Mikrofon geschlossen.
+                // _playback_stream bleibt im Scope am Leben
+                let mut encoder = encoder;
+                let mut bitrate = audio_bitrate;
+                let mut hardware_stumm = audio_hardware_stumm;
+                let mut dtx = audio_dtx;
+                let mut stoergeraeusch =
+                    StoergeraeuschErkennung::neu(SAMPLE_RATE, StoergeraeuschKonfig::default());
+                let mut pipeline = Self::capture_pipeline(audio_echo);
+                loop {
+                    let zustand = *audio_steuer_rx.borrow_and_update();
+                    if !zustand.gilt_fuer(lauf) {
+                        break;
+                    }
+                    if zustand.nur_hoeren {
+                        if capture.take().is_some() {
+                            audio_sprechen.setzen(false);
+                            debug!("Nur-Zuhoeren: Capture-Stream geschlossen");
+                        }
+                        std::thread::sleep(NUR_HOEREN_PAUSE);
+                        continue;
+                    }
+                    if capture.is_none() {
+                        match Self::capture_oeffnen() {
+                            Ok(streams) => capture = Some(streams),
+                            Err(e) => {
+                                // Ohne Mikrofon bleibt nur das Zuhoeren
+                                error!("Capture-Stream konnte nicht geoeffnet werden: {}", e);
+                                audio_steuerung.set_nur_hoeren(true);
+                                continue;
+                            }
+                        }
+                    }
+                    if let Some((_capture_stream, capture_consumer)) = capture.as_mut() {
+                        Self::sende_loop(
+                            capture_consumer,
+                            &mut pipeline,
+                            &send_socket,
+                            audio_server_addr,
+                            audio_ssrc,
+                            &mut encoder,
+                            &mut bitrate,
+                            &mut dtx,
+                            &mut hardware_stumm,
+                            &mut stoergeraeusch,
+                            &audio_ereignisse,
+                            &mut audio_steuer_rx,
+                            lauf,
+                            &audio_ptt,
+                            &audio_sprechen,
+                            &audio_sequence,
+                            &audio_trace,
+                            &audio_codec_zaehler,
+                            &audio_pakete,
+                            audio_verschluesselung.as_deref(),
+                        );
+                    }
+                }
 
                 debug!("Audio-Thread beendet, cpal-Streams werden gedroppt");
                 // _capture_stream und _playback_stream werden hier gedroppt
             })
-            .map_err(|e| format!("Audio-Thread konnte nicht gestartet werden: {}", e))?;
+            .map_err(|e| {
+                VoiceStartFehler::Audio(format!(
+                    "Audio-Thread konnte nicht gestartet werden: {}",
+                    e
+                ))
+            })?;
+        // Ab hier joint ein Abbruch den Thread wieder
+        self.audio_thread = Some(audio_thread);
 
         // PlaybackProducer vom Audio-Thread empfangen
-        let playback_producer = producer_rx
+        let (playback_producer, effekt_producer) = producer_rx
             .recv()
-            .map_err(|_| "Audio-Streams konnten nicht initialisiert werden".to_string())?;
-
-        // 3. Empfangs-Task starten (async)
-        let (shutdown_tx, shutdown_rx) = tokio::sync::oneshot::channel::<()>();
-        let recv_running = Arc::clone(&self.running);
+            .unwrap_or_else(|_| Err("Audio-Streams konnten nicht initialisiert werden".into()))
+            .map_err(VoiceStartFehler::Audio)?;
+        if let Ok(mut effekte) = self.effekte.lock() {
+            *effekte = Some(effekt_producer);
+        }
 
-        let recv_task = tokio::spawn(Self::empfangs_loop(
+        // 4. Empfangs-Task starten (async), meldet den Endpunkt per Hello an
+        let hello = self
+            .hello_nonce
+            .clone()
+            .map(|nonce| HelloAnmeldung::neu(server_addr, ssrc, nonce));
+        self.recv_task = Some(tokio::spawn(Self::empfangs_loop(
             socket,
+            hello,
             playback_producer,
-            opus_config,
-            recv_running,
-            deafened,
-            shutdown_rx,
-        ));
-
-        self.running.store(true, Ordering::Relaxed);
-        self.shutdown_tx = Some(shutdown_tx);
-        self.audio_thread = Some(audio_thread);
-        self.recv_task = Some(recv_task);
+            dekoder,
+            EmpfangsMischer::neu(Arc::clone(&self.benutzer_pegel)),
+            EmpfangsPuffer::neu(self.jitter),
+            self.steuerung.abonnieren(),
+            lauf,
+            Arc::clone(&self.statistik),
+            self.pakete.clone(),
+            Arc::clone(&self.trace),
+            self.verschluesselung.clone(),
+            referenz,
+        )));
 
-        info!("Voice-Pipeline gestartet");
         Ok(())
     }
 
     /// Stoppt die Voice-Pipeline sauber
+    ///
+    /// Setzt erst den Zustand auf gestoppt und wartet dann je hoechstens
+    /// [`STOP_FRIST`] auf Empfangs-Task und Audio-Thread.
     pub async fn stop(&mut self) {
-        if !self.running.load(Ordering::Relaxed) {
+        if !self.steuerung.stoppen() {
             return;
         }
 
         info!("Stoppe Voice-Pipeline");
+        self.abbauen().await;
+        info!("Voice-Pipeline gestoppt");
+    }
 
-        self.running.store(false, Ordering::Relaxed);
-
-        // Shutdown-Signal an Empfangs-Task senden
-        if let Some(tx) = self.shutdown_tx.take() {
-            let _ = tx.send(());
+    /// Wartet auf das Ende von Empfangs-Task und Audio-Thread eines bereits
+    /// gestoppten Laufs
+    async fn abbauen(&mut self) {
+        if let Ok(mut effekte) = self.effekte.lock() {
+            *effekte = None;
         }
 
-        // Auf Empfangs-Task warten
-        if let Some(handle) = self.recv_task.take() {
-            let _ = handle.await;
+        if let Some(mut handle) = self.recv_task.take() {
+            if tokio::time::timeout(STOP_FRIST, &mut handle).await.is_err() {
+                warn!("Empfangs-Task reagiert nicht auf den Stopp, wird abgebrochen");
+                handle.abort();
+            }
         }
 
-        // Auf Audio-Thread warten (haelt cpal-Streams, Sende-Loop)
+        // Audio-Thread haelt cpal-Streams und Sende-Loop
         if let Some(handle) = self.audio_thread.take() {
-            let _ = handle.join();
+            voice_steuerung::thread_joinen(handle, STOP_FRIST).await;
         }
+    }
 
-        info!("Voice-Pipeline gestoppt");
+    /// Nur-Zuhoeren ein-/ausschalten
+    ///
+    /// Als Nur-Zuhoerer oeffnet die Pipeline kein Mikrofon; bei laufender
+    /// Pipeline wird der Capture-Stream geschlossen bzw. neu geoeffnet. Der
+    /// Server verwirft Pakete von Nur-Zuhoerern ohnehin.
+    pub fn set_listen_only(&self, nur_hoeren: bool) {
+        self.steuerung.set_nur_hoeren(nur_hoeren);
+        info!("Voice Nur-Zuhoeren: {}", nur_hoeren);
+    }
+
+    /// Ist die Pipeline im Nur-Zuhoeren-Modus?
+    pub fn is_listen_only(&self) -> bool {
+        self.steuerung.zustand().nur_hoeren
     }
 
     /// Mikrofon muten/unmuten
     ///
     /// Bei Mute: Sende-Thread sendet nichts, Empfang laeuft weiter
     pub fn set_muted(&self, muted: bool) {
-        self.muted.store(muted, Ordering::Relaxed);
+        self.steuerung.set_muted(muted);
         info!("Voice Mute: {}", muted);
     }
 
+    /// Sperre durch die Notfall-Stummschaltung des Kanals setzen/aufheben
+    ///
+    /// Reine Hoeflichkeit: der Server verwirft die Pakete ohnehin. Der eigene
+    /// Mute-Zustand bleibt unberuehrt.
+    pub fn set_emergency_muted(&self, gesperrt: bool) {
+        if self.steuerung.set_notfall(gesperrt) {
+            info!("Voice Notfall-Stummschaltung: {}", gesperrt);
+        }
+    }
+
     /// Ton deaktivieren/aktivieren (deaf)
     ///
-    /// Bei Deaf: Empfangs-Thread verwirft Pakete, Sende-Thread stoppt ebenfalls
+    /// Bei Deaf: Empfangs-Thread verwirft Pakete, Sende-Thread stoppt ebenfalls.
+    /// Der eigene Mute-Zustand bleibt erhalten und gilt nach dem Undeafen wieder.
     pub fn set_deafened(&self, deafened: bool) {
-        self.deafened.store(deafened, Ordering::Relaxed);
-        if deafened {
-            // Deaf impliziert Mute
-            self.muted.store(true, Ordering::Relaxed);
-        }
+        self.steuerung.set_deafened(deafened);
         info!("Voice Deaf: {}", deafened);
     }
 
+    /// Setzt die maximale Absenkung der Sprache waehrend Event-Sounds (0.0 - 1.0)
+    pub fn set_event_ducking(&self, anteil: f32) {
+        self.ducking.setzen(anteil);
+    }
+
+    /// Mischt einen Event-Sound in die Wiedergabe
+    ///
+    /// Gibt `false` zurueck wenn die Pipeline nicht laeuft. Passt der Sound
+    /// nicht mehr vollstaendig in den Puffer, wird der Rest verworfen.
+    pub fn effekt_abspielen(&self, samples: &[f32]) -> bool {
+        let Ok(mut effekte) = self.effekte.lock() else {
+            return false;
+        };
+        let Some(producer) = effekte.as_mut() else {
+            return false;
+        };
+        let geschrieben = producer.push_slice(samples);
+        if geschrieben < samples.len() {
+            trace!(
+                "Effekt-Puffer voll: {} von {} Samples geschrieben",
+                geschrieben,
+                samples.len()
+            );
+        }
+        true
+    }
+
     /// Gibt zurueck ob der Benutzer gerade spricht
     pub fn is_speaking(&self) -> bool {
         self.speaking.load(Ordering::Relaxed)
@@ -284,7 +1027,7 @@ impl VoiceClient {
 
     /// Gibt zurueck ob die Pipeline laeuft
     pub fn is_running(&self) -> bool {
-        self.running.load(Ordering::Relaxed)
+        self.steuerung.zustand().laeuft()
     }
 
     /// Gibt die zugewiesene SSRC zurueck
@@ -292,68 +1035,267 @@ impl VoiceClient {
         self.ssrc
     }
 
-    /// Gibt den lokalen UDP-Port zurueck (fuer VoiceInit)
+    /// Beim letzten Start ausgehandelter Codec
+    pub fn codec(&self) -> AudioCodec {
+        self.codec
+    }
+
+    /// Setzt die Opus-Konfiguration fuer den naechsten Start der Pipeline
+    pub fn set_opus_config(&mut self, opus_config: OpusConfig) {
+        self.opus_config = opus_config;
+    }
+
+    /// Uebernimmt die beim Voice-Init vereinbarte Opus-Konfiguration
+    fn ausgehandelt_uebernehmen(&mut self, antwort: &CodecNegotiationResponse) {
+        if antwort.status == NegotiationStatus::Adjusted {
+            info!(
+                grund = antwort.adjustment_reason.as_deref().unwrap_or_default(),
+                bitrate_kbps = antwort.accepted.bitrate_kbps,
+                "Server hat die Opus-Konfiguration angepasst"
+            );
+        }
+        self.opus_config = antwort.accepted.clone();
+        self.preset = antwort.preset.unwrap_or(STANDARD_PRESET);
+    }
+
+    /// Setzt die Groesse des Jitter-Puffers fuer den naechsten Start
+    pub fn set_jitter(&mut self, jitter: JitterEinstellung) {
+        self.jitter = jitter;
+    }
+
+    /// Gibt alle seit dem letzten Aufruf aufgetretenen Ereignisse zurueck
+    pub fn ereignisse_abholen(&self) -> Vec<VoiceEreignis> {
+        self.ereignisse
+            .lock()
+            .map(|mut e| std::mem::take(&mut *e))
+            .unwrap_or_default()
+    }
+
+    /// Hoechste bisher gesendete Sequenznummer (None = noch nichts gesendet)
+    pub fn hoechste_gesendete_sequenz(&self) -> Option<u32> {
+        self.sequence.load(Ordering::Relaxed).checked_sub(1)
+    }
+
+    /// Aktuelle Sende-Bitrate (kbps): Vorgabe des Servers, begrenzt auf das Preset
+    pub fn bitrate_kbps(&self) -> u16 {
+        match self.ziel_bitrate.load(Ordering::Relaxed) {
+            0 => self.opus_config.bitrate_kbps,
+            ziel => self
+                .preset
+                .bitrate_begrenzen(u16::try_from(ziel).unwrap_or(u16::MAX)),
+        }
+    }
+
+    /// Unterlaeufe des Playback-Ring-Buffers seit Erstellung des Clients
+    pub fn playback_unterlaeufe(&self) -> u64 {
+        self.playback_unterlauf.unterlaeufe()
+    }
+
+    /// Codec-Fehler je Kategorie seit Erstellung des Clients
+    pub fn codec_statistik(&self) -> CodecStatistik {
+        self.codec_zaehler.statistik()
+    }
+
+    /// Gesendete und empfangene Voice-Pakete seit Erstellung des Clients
+    pub fn paket_zaehler(&self) -> &PaketZaehler {
+        &self.pakete
+    }
+
+    /// Lokale Adresse des UDP-Sockets (`None` = Pipeline nie gestartet)
+    pub fn lokale_adresse(&self) -> Option<SocketAddr> {
+        self.lokale_adresse
+    }
+
+    /// Server-Adresse fuer Voice-Pakete
+    pub fn server_adresse(&self) -> SocketAddr {
+        self.server_addr
+    }
+
+    /// Beim Voice-Init ausgehandelte Sitzung (Transport, Krypto-Modus)
+    pub fn sitzung(&self) -> Option<&VoiceSitzung> {
+        self.sitzung.as_ref()
+    }
+
+    /// Dauer eines Sprach-Frames laut Opus-Konfiguration (ms)
+    pub fn frame_ms(&self) -> f32 {
+        self.opus_config.frame_size.as_ms()
+    }
+
+    /// Paketverlust-Statistik der laufenden Sitzung
+    pub fn statistik(&self) -> Arc<Mutex<VerbindungsStatistik>> {
+        Arc::clone(&self.statistik)
+    }
+
+    /// Setzt den DSCP-Wert fuer den naechsten Start der Pipeline
+    pub fn set_dscp(&mut self, dscp: Option<u8>) {
+        self.dscp = dscp;
+    }
+
+    /// Setzt den Paket-Trace fuer den naechsten Start der Pipeline
+    pub fn set_trace(&mut self, trace: Arc<VoiceTrace>) {
+        self.trace = trace;
+    }
+
+    /// Setzt die Lautstaerke pro Benutzer fuer den naechsten Start der Pipeline
+    pub fn set_benutzer_pegel(&mut self, pegel: Arc<BenutzerPegel>) {
+        self.benutzer_pegel = pegel;
+    }
+
+    /// Teilt die PTT-Freigabe mit dem AppState fuer den naechsten Start
+    pub fn set_ptt_freigabe(&mut self, freigabe: Arc<SendeFreigabe>) {
+        self.ptt_freigabe = freigabe;
+    }
+
+    /// Setzt den Melder fuer Wechsel des Sprech-Zustands (naechster Start)
+    pub fn set_sprech_melder(&mut self, melder: SprechMelder) {
+        self.sprech_melder = Some(melder);
+    }
+
+    /// Setzt die Hardware-Stumm-Erkennung fuer den naechsten Start der
+    /// Pipeline (`None` = aus, sonst Dauer des Null-Signals)
+    pub fn set_hardware_stumm(&mut self, null_dauer: Option<std::time::Duration>) {
+        self.hardware_stumm = null_dauer;
+    }
+
+    /// Setzt DTX fuer den naechsten Start der Pipeline (`None` = jeden
+    /// Frame senden, sonst Abstand der Silence-Pakete in Sprechpausen)
+    pub fn set_dtx(&mut self, keepalive: Option<std::time::Duration>) {
+        self.dtx_keepalive = keepalive;
+    }
+
+    /// Setzt die Echo-Unterdrueckung fuer den naechsten Start der Pipeline
+    /// (`None` = aus)
+    pub fn set_echo_unterdrueckung(&mut self, echo: Option<EchoCancelConfig>) {
+        self.echo = echo;
+    }
+
+    /// Teilt die Ziel-Bitrate mit der Verbindung fuer den naechsten Start
+    pub fn set_ziel_bitrate(&mut self, ziel: Arc<AtomicU32>) {
+        self.ziel_bitrate = ziel;
+    }
+
+    /// Merkt sich die ausgehandelte Sitzung fuer den Debugzustand
+    pub fn set_sitzung(&mut self, sitzung: VoiceSitzung) {
+        self.sitzung = Some(sitzung);
+    }
+
+    /// Setzt die Nonce fuer das Hello beim naechsten Start
+    pub fn set_hello_nonce(&mut self, nonce: Option<Vec<u8>>) {
+        self.hello_nonce = nonce;
+    }
+
+    /// Schaltet die E2E-Verschluesselung fuer den naechsten Start ein
+    /// (`None` = Modus `none` oder `dtls`)
+    pub fn set_verschluesselung(&mut self, schluessel: Option<Arc<VoiceSchluessel>>) {
+        self.verschluesselung = schluessel;
+    }
+
+    /// Momentaufnahme von Steuer-Zustand, Konfiguration und Zaehlern
+    ///
+    /// Liest nur Atomics und Kopien; ist die Verlust-Statistik gerade vom
+    /// Empfangs-Loop belegt, fehlen die Verlustwerte.
+    pub fn debug_zustand(&self) -> PipelineDebug {
+        let zustand = self.steuerung.zustand();
+        let verlust = self
+            .statistik
+            .try_lock()
+            .ok()
+            .map(|s| (s.uplink_verlust_prozent(), s.downlink_verlust_prozent()));
+        PipelineDebug {
+            running: zustand.laeuft(),
+            muted: zustand.muted,
+            deafened: zustand.deafened,
+            listen_only: zustand.nur_hoeren,
+            emergency_muted: zustand.notfall_gesperrt,
+            speaking: self.is_speaking(),
+            ssrc: self.ssrc,
+            server_addr: self.server_addr.to_string(),
+            codec: self.codec.name().to_string(),
+            opus: self.opus_config.clone(),
+            bitrate_kbps: self.bitrate_kbps(),
+            jitter: JitterDebug {
+                min_frames: self.jitter.min_pakete,
+                max_frames: self.jitter.max_pakete,
+                budget_frames: self.jitter.budget_pakete,
+                adaptive: self.jitter.adaptiv,
+            },
+            dtx_keepalive_ms: self.dtx_keepalive.map(|d| d.as_millis() as u64),
+            hardware_mute_ms: self.hardware_stumm.map(|d| d.as_millis() as u64),
+            voice_qos: self.qos.clone(),
+            session: self.sitzung.clone(),
+            counters: PipelineZaehler {
+                highest_sequence: self.hoechste_gesendete_sequenz(),
+                playback_underruns: self.playback_unterlaeufe(),
+                codec_errors: self.codec_statistik().into(),
+                uplink_loss: verlust.map(|(uplink, _)| uplink),
+                downlink_loss: verlust.map(|(_, downlink)| downlink),
+            },
+        }
+    }
+
+    /// Gibt zurueck ob und wie der UDP-Socket markiert ist
+    pub fn qos_status(&self) -> &QosStatus {
+        &self.qos
+    }
+
+    /// Gibt den lokalen UDP-Port zurueck (0 = noch nicht gebunden)
     pub fn local_udp_port(&self) -> u16 {
-        // Wird beim Start gesetzt
-        0
+        self.lokale_adresse.map_or(0, |a| a.port())
     }
 
     // -----------------------------------------------------------------------
     // Audio-Streams oeffnen
     // -----------------------------------------------------------------------
 
-    /// Oeffnet Capture- und Playback-Streams
-    fn start_audio_streams() -> Result<
-        (
-            speakeasy_audio::capture::CaptureStream,
-            speakeasy_audio::CaptureConsumer,
-            speakeasy_audio::playback::PlaybackStream,
-            speakeasy_audio::PlaybackProducer,
-        ),
-        String,
-    > {
+    /// Oeffnet den Playback-Stream inkl. Effekt-Quelle
+    fn playback_oeffnen(
+        ducking: DuckingRegler,
+        unterlauf_zaehler: UnterlaufZaehler,
+    ) -> Result<PlaybackStreams, String> {
         use cpal::traits::HostTrait;
 
-        let host = cpal::default_host();
-
-        // Eingabegeraet
-        let input_device = host
-            .default_input_device()
-            .ok_or_else(|| "Kein Standard-Eingabegeraet gefunden".to_string())?;
+        let output_device = cpal::default_host()
+            .default_output_device()
+            .ok_or_else(|| "Kein Standard-Ausgabegeraet gefunden".to_string())?;
 
-        let capture_config = speakeasy_audio::CaptureConfig {
+        let playback_config = speakeasy_audio::PlaybackConfig {
             sample_rate: SAMPLE_RATE,
             channels: 1,
-            buffer_size: SAMPLE_RATE as usize * 2, // 2 Sekunden
+            buffer_size: SAMPLE_RATE as usize * 2,
+            unterlauf_zaehler,
+            ..Default::default()
         };
 
-        let (capture_stream, capture_consumer) =
-            speakeasy_audio::capture::open_capture_stream(&input_device, capture_config)
-                .map_err(|e| format!("Capture-Stream konnte nicht geoeffnet werden: {}", e))?;
+        let streams = speakeasy_audio::playback::open_playback_stream_mit_effekten(
+            &output_device,
+            playback_config,
+            ducking,
+        )
+        .map_err(|e| format!("Playback-Stream konnte nicht geoeffnet werden: {}", e))?;
 
-        // Ausgabegeraet
-        let output_device = host
-            .default_output_device()
-            .ok_or_else(|| "Kein Standard-Ausgabegeraet gefunden".to_string())?;
+        debug!("Playback-Stream geoeffnet");
+        Ok(streams)
+    }
 
-        let playback_config = speakeasy_audio::PlaybackConfig {
+    /// Oeffnet den Capture-Stream des Standard-Eingabegeraets
+    fn capture_oeffnen() -> Result<CaptureStreams, String> {
+        use cpal::traits::HostTrait;
+
+        let input_device = cpal::default_host()
+            .default_input_device()
+            .ok_or_else(|| "Kein Standard-Eingabegeraet gefunden".to_string())?;
+
+        let capture_config = speakeasy_audio::CaptureConfig {
             sample_rate: SAMPLE_RATE,
             channels: 1,
-            buffer_size: SAMPLE_RATE as usize * 2,
+            buffer_size: SAMPLE_RATE as usize * 2, // 2 Sekunden
         };
 
-        let (playback_stream, playback_producer) =
-            speakeasy_audio::playback::open_playback_stream(&output_device, playback_config)
-                .map_err(|e| format!("Playback-Stream konnte nicht geoeffnet werden: {}", e))?;
-
-        debug!("Audio-Streams geoeffnet (Capture + Playback)");
+        let streams = speakeasy_audio::capture::open_capture_stream(&input_device, capture_config)
+            .map_err(|e| format!("Capture-Stream konnte nicht geoeffnet werden: {}", e))?;
 
-        Ok((
-            capture_stream,
-            capture_consumer,
-            playback_stream,
-            playback_producer,
-        ))
+        debug!("Capture-Stream geoeffnet");
+        Ok(streams)
     }
 
     // -----------------------------------------------------------------------
@@ -361,29 +1303,40 @@ impl VoiceClient {
     // -----------------------------------------------------------------------
 
     /// Sende-Loop: Liest Frames aus dem Capture-Ring-Buffer, verarbeitet sie
-    /// durch die DSP-Pipeline, enkodiert mit Opus und sendet per UDP.
+    /// durch die DSP-Pipeline, enkodiert mit dem ausgehandelten Codec und
+    /// sendet per UDP. Kehrt zurueck, wenn die Pipeline stoppt oder in den
+    /// Nur-Zuhoeren-Modus wechselt.
     fn sende_loop(
-        mut capture_consumer: speakeasy_audio::CaptureConsumer,
-        socket: Arc<UdpSocket>,
+        capture_consumer: &mut speakeasy_audio::CaptureConsumer,
+        pipeline: &mut AudioPipeline,
+        socket: &UdpSocket,
         server_addr: SocketAddr,
         ssrc: u32,
-        opus_config: OpusConfig,
-        running: Arc<AtomicBool>,
-        muted: Arc<AtomicBool>,
-        speaking: Arc<AtomicBool>,
-        sequence: Arc<AtomicU32>,
+        encoder: &mut SprachEncoder,
+        bitrate: &mut BitrateVorgabe,
+        dtx: &mut Dtx,
+        hardware_stumm: &mut Option<HardwareStummErkennung>,
+        stoergeraeusch: &mut StoergeraeuschErkennung,
+        ereignisse: &Mutex<Vec<VoiceEreignis>>,
+        steuer_rx: &mut watch::Receiver<SteuerZustand>,
+        lauf: u64,
+        ptt: &SendeFreigabe,
+        sprechen: &SprechZustand,
+        sequence: &AtomicU32,
+        voice_trace: &VoiceTrace,
+        codec_zaehler: &CodecZaehler,
+        pakete: &PaketZaehler,
+        verschluesselung: Option<&VoiceSchluessel>,
     ) {
-        // Opus Encoder erstellen
-        let mut encoder = match OpusEncoder::new(opus_config) {
-            Ok(enc) => enc,
-            Err(e) => {
-                error!("Opus-Encoder konnte nicht erstellt werden: {}", e);
-                return;
-            }
+        // Empfaenger erkennen PCMU-Nutzdaten am Flag
+        let mut codec_flag = match encoder.codec() {
+            AudioCodec::Opus => 0,
+            AudioCodec::Pcmu => VoiceFlags::PCMU,
         };
-
-        // DSP-Pipeline erstellen (minimale Pipeline, kein Panic in Audio-Thread)
-        let mut pipeline = build_minimal_capture_pipeline();
+        // Mit FEC kann der Empfaenger einen verlorenen Frame rekonstruieren
+        if encoder.fec_aktiv() {
+            codec_flag |= VoiceFlags::FEC;
+        }
 
         // Frame-Buffer fuer das Sammeln von Samples
         let frame_size = encoder.frame_size();
@@ -395,7 +1348,13 @@ impl VoiceClient {
 
         debug!("Sende-Loop gestartet (frame_size={})", frame_size);
 
-        while running.load(Ordering::Relaxed) {
+        loop {
+            // Ein zusammenhaengender Schnappschuss je Durchlauf
+            let zustand = *steuer_rx.borrow_and_update();
+            if !zustand.gilt_fuer(lauf) || zustand.nur_hoeren {
+                break;
+            }
+
             // Samples aus dem Ring-Buffer lesen
             let available = capture_consumer.pop_slice(&mut temp_buf);
 
@@ -412,49 +1371,101 @@ impl VoiceClient {
             while frame_buffer.len() >= frame_size {
                 let frame: Vec<f32> = frame_buffer.drain(..frame_size).collect();
 
-                // Gemutet? -> Nichts senden
-                if muted.load(Ordering::Relaxed) {
+                // Hardware-Stummschaltung am rohen Signal erkennen, auch
+                // wenn logisch gemutet ist
+                if let Some(stumm) = hardware_stumm.as_mut().and_then(|e| e.verarbeiten(&frame)) {
+                    info!(
+                        "Hardware-Stummschaltung {}",
+                        if stumm { "erkannt" } else { "aufgehoben" }
+                    );
+                    ereignis_melden(
+                        ereignisse,
+                        VoiceEreignis::HardwareMuteDetected { muted: stumm },
+                    );
+                }
+
+                // Gemutet, deaf, Notfall-Stummschaltung oder PTT-Taste
+                // nicht gedrueckt? -> Nichts senden
+                if zustand.senden_gesperrt() || ptt.gesperrt() {
+                    let (timestamp, _) = dtx.frame(false);
                     if was_speaking {
-                        speaking.store(false, Ordering::Relaxed);
+                        // Letztes Paket beendet die Sprech-Anzeige der anderen
+                        let seq = sequence.fetch_add(1, Ordering::Relaxed);
+                        let paket = stop_paket(seq, timestamp, ssrc);
+                        Self::paket_senden(socket, server_addr, &paket, voice_trace, pakete);
+                        sprechen.setzen(false);
                         was_speaking = false;
                     }
                     continue;
                 }
 
                 // DSP-Pipeline
-                let processed = pipeline.process_frame(&frame);
+                let mut processed = pipeline.process_frame(&frame);
 
-                // Sprach-Erkennung: RMS-Pegel pruefen
+                // Sprach-Erkennung: RMS-Pegel pruefen (bei PTT entscheidet
+                // allein die Taste)
                 let rms = rms_level(&processed.samples);
-                let is_voice = rms > 0.005; // -46 dBFS Schwelle
+                let is_voice = ptt.ptt() || rms > 0.005; // -46 dBFS Schwelle
 
                 // Speaking-Flags fuer den Header
-                let mut flags: u16 = 0;
+                let mut flags: u16 = codec_flag;
                 if is_voice && !was_speaking {
                     flags |= VoiceFlags::SPEAKING_START;
-                    speaking.store(true, Ordering::Relaxed);
+                    sprechen.setzen(true);
                 } else if !is_voice && was_speaking {
                     flags |= VoiceFlags::SPEAKING_STOP;
-                    speaking.store(false, Ordering::Relaxed);
+                    sprechen.setzen(false);
                 }
                 was_speaking = is_voice;
 
-                // Opus Encode
-                let opus_bytes = match encoder.encode(&processed.samples) {
-                    Ok(bytes) => bytes,
-                    Err(e) => {
-                        warn!("Opus-Encoding fehlgeschlagen: {}", e);
+                // Ueberwiegend Hintergrundgeraeusche im gesendeten Signal?
+                if let Some(score) = stoergeraeusch.verarbeiten(&processed.samples, is_voice) {
+                    info!("Hintergrundgeraeusche erkannt (Anteil {:.2})", score);
+                    ereignis_melden(ereignisse, VoiceEreignis::BackgroundNoiseWarning { score });
+                }
+
+                // DTX: Der Zeitstempel laeuft mit jedem Frame weiter, in
+                // Sprechpausen geht aber nur ab und zu ein Paket raus
+                let (timestamp, senden) = dtx.frame(is_voice);
+                if !senden {
+                    continue;
+                }
+
+                // Neue Ziel-Bitrate vom Server ohne Neustart uebernehmen
+                match bitrate.anwenden(encoder) {
+                    Ok(Some(kbps)) => debug!("Encoder-Bitrate auf {} kbps angepasst", kbps),
+                    Ok(None) => {}
+                    Err(e) => warn!("Bitrate konnte nicht angepasst werden: {}", e),
+                }
+
+                // Encode (nur Sprache, Silence-Pakete sind leer)
+                let nutzdaten = if is_voice {
+                    // Nur Frames der ausgehandelten Groesse erreichen den Encoder
+                    if let Err(e) =
+                        frame_angleichen(&mut processed.samples, frame_size, codec_zaehler)
+                    {
+                        warn!("Frame verworfen: {}", e);
                         continue;
                     }
+                    match encoder.encode(&processed.samples) {
+                        Ok(bytes) => Some(bytes),
+                        Err(e) => {
+                            codec_zaehler.fehler_erfassen(&e);
+                            warn!("Encoding fehlgeschlagen: {}", e);
+                            continue;
+                        }
+                    }
+                } else {
+                    None
                 };
 
-                // Sequenz und Zeitstempel
-                let seq = sequence.fetch_add(1, Ordering::Relaxed);
-                let timestamp = seq * frame_size as u32;
+                // Sequenz zaehlt nur gesendete Pakete; hochgezaehlt wird erst
+                // nach dem Verschluesseln (nur dieser Thread zaehlt)
+                let seq = sequence.load(Ordering::Relaxed);
 
                 // VoicePacket erstellen
-                let paket = if is_voice {
-                    VoicePacket {
+                let mut paket = match nutzdaten {
+                    Some(payload) => VoicePacket {
                         header: VoicePacketHeader::new(
                             speakeasy_protocol::voice::PacketType::Audio,
                             flags,
@@ -462,75 +1473,152 @@ impl VoiceClient {
                             timestamp,
                             ssrc,
                         ),
-                        payload: opus_bytes,
+                        payload,
+                    },
+                    // Erstes Silence-Paket nach dem Sprechen traegt das Ende
+                    None if flags & VoiceFlags::SPEAKING_STOP != 0 => {
+                        stop_paket(seq, timestamp, ssrc)
                     }
-                } else {
-                    // Silence-Paket (DTX)
-                    VoicePacket::neu_silence(seq, timestamp, ssrc)
+                    // Silence-Paket als Keepalive (DTX)
+                    None => VoicePacket::neu_silence(seq, timestamp, ssrc),
                 };
 
-                // UDP senden (blockierend im spawn_blocking-Kontext)
-                let encoded = paket.encode();
-
-                // Wir nutzen try_send via std::net::UdpSocket, da wir in einem
-                // blockierenden Thread laufen. socket.try_send_to blockiert nicht.
-                if let Err(e) = socket.try_send_to(&encoded, server_addr) {
-                    trace!("UDP-Sendefehler: {}", e);
+                // E2E: Audio nur verschluesselt, ohne Gruppenschluessel gar nicht
+                if let Some(schluessel) = verschluesselung.filter(|_| !paket.payload.is_empty()) {
+                    if let Err(e) = schluessel.paket_verschluesseln(&mut paket) {
+                        trace!("Frame nicht verschluesselt, verworfen: {}", e);
+                        continue;
+                    }
                 }
+                sequence.fetch_add(1, Ordering::Relaxed);
+
+                Self::paket_senden(socket, server_addr, &paket, voice_trace, pakete);
             }
         }
 
         debug!("Sende-Loop beendet");
     }
 
+    /// Sendet ein Voice-Paket und zeichnet es im Trace auf
+    fn paket_senden(
+        socket: &UdpSocket,
+        server_addr: SocketAddr,
+        paket: &VoicePacket,
+        voice_trace: &VoiceTrace,
+        pakete: &PaketZaehler,
+    ) {
+        let encoded = paket.encode();
+
+        // Wir nutzen try_send via std::net::UdpSocket, da wir in einem
+        // blockierenden Thread laufen. socket.try_send_to blockiert nicht.
+        match socket.try_send_to(&encoded, server_addr) {
+            Ok(_) => {
+                pakete.gesendet_erfassen();
+                voice_trace.aufzeichnen(
+                    Richtung::Gesendet,
+                    &paket.header,
+                    encoded.len(),
+                    &paket.payload,
+                );
+            }
+            Err(e) => trace!("UDP-Sendefehler: {}", e),
+        }
+    }
+
     // -----------------------------------------------------------------------
     // Empfangs-Loop (async, laeuft in Tokio-Task)
     // -----------------------------------------------------------------------
 
-    /// Empfangs-Loop: Empfaengt UDP-Pakete, dekodiert Opus und schreibt in den
+    /// Empfangs-Loop: Empfaengt UDP-Pakete in den Jitter-Puffer, entnimmt sie
+    /// im Abspieltakt, dekodiert sie je SSRC und schreibt in den
     /// Playback-Ring-Buffer.
+    /// DSP-Pipeline des Sende-Pfads (minimale Pipeline, kein Panic im
+    /// Audio-Thread), optional mit Echo-Unterdrueckung am Ende
+    fn capture_pipeline(echo: Option<EchoCanceller>) -> AudioPipeline {
+        let mut pipeline = build_minimal_capture_pipeline();
+        if let Some(echo) = echo {
+            pipeline.push(Box::new(echo));
+        }
+        pipeline
+    }
+
     async fn empfangs_loop(
         socket: Arc<UdpSocket>,
+        mut hello: Option<HelloAnmeldung>,
         mut playback_producer: speakeasy_audio::PlaybackProducer,
-        opus_config: OpusConfig,
-        running: Arc<AtomicBool>,
-        deafened: Arc<AtomicBool>,
-        mut shutdown_rx: tokio::sync::oneshot::Receiver<()>,
+        mut dekoder: StreamDekoder,
+        mut mischer: EmpfangsMischer,
+        mut jitter: EmpfangsPuffer,
+        mut steuer_rx: watch::Receiver<SteuerZustand>,
+        lauf: u64,
+        statistik: Arc<Mutex<VerbindungsStatistik>>,
+        pakete: PaketZaehler,
+        voice_trace: Arc<VoiceTrace>,
+        verschluesselung: Option<Arc<VoiceSchluessel>>,
+        mut referenz: Option<EchoReferenz>,
     ) {
-        // Opus Decoder erstellen
-        let mut decoder =
-            match OpusDecoder::from_config(&opus_config) {
-                Ok(dec) => dec,
-                Err(e) => {
-                    error!("Opus-Decoder konnte nicht erstellt werden: {}", e);
-                    return;
-                }
-            };
-
-        // Volume Controller (wird spaeter fuer per-User Volume genutzt)
-        let _volume = VolumeController::new();
-
         let mut buf = [0u8; UDP_BUFFER_SIZE];
 
+        // Kernel-Zaehler des Sockets: verworfene Datagramme sind kein Netzwerkverlust
+        let mut socket_pruefung = tokio::time::interval(SOCKET_PRUEF_INTERVALL);
+        let mut drops = DropErkennung::new();
+        let mut socket_zaehler_verfuegbar = true;
+        // ICMP-Rueckmeldungen (z.B. Server kurz nicht erreichbar)
+        let mut unerreichbar: u64 = 0;
+        let mut abspiel_takt = tokio::time::interval(voice_jitter::TAKT);
+        // Ein Frame je Takt und Sprecher; wer schneller liefert, laeuft ueber
+        let mut wiedergabe = WiedergabeMischer::neu(MischerKonfig::default());
+        let mut ausgabe_frame = vec![0.0f32; wiedergabe.konfig().frame_samples];
+        // Stand des Jitter-Puffers fuer die Qualitaetsschaetzung
+        let mut puffer_meldung = tokio::time::interval(voice_stats::BERICHT_INTERVALL);
+        let mut hello_takt = tokio::time::interval(HELLO_INTERVALL);
+
         debug!("Empfangs-Loop gestartet");
 
         loop {
             tokio::select! {
+                // Bis zum ersten Paket vom Server wiederholen
+                _ = hello_takt.tick(), if hello.is_some() => {
+                    if let Some(anmeldung) = hello.as_mut() {
+                        if !anmeldung.senden(&socket).await {
+                            warn!("Keine Antwort auf Hello, Server-Endpunkt nicht bestaetigt");
+                            hello = None;
+                        }
+                    }
+                }
+
+                _ = socket_pruefung.tick(), if socket_zaehler_verfuegbar => {
+                    let Some(zaehler) = socket_statistik::zaehler_lesen(SockRef::from(&*socket))
+                    else {
+                        debug!("Socket-Zaehler auf dieser Plattform nicht verfuegbar");
+                        socket_zaehler_verfuegbar = false;
+                        continue;
+                    };
+                    let neu = drops.neue_drops(&zaehler);
+                    if neu > 0 {
+                        warn!(
+                            verworfen = neu,
+                            gesamt = zaehler.verworfen,
+                            hinweis = ABHILFE_HINWEIS,
+                            "System hat empfangene Voice-Datagramme verworfen"
+                        );
+                    }
+                    if let Ok(mut stat) = statistik.lock() {
+                        stat.socket_zaehler_setzen(zaehler);
+                    }
+                }
+
                 result = socket.recv_from(&mut buf) => {
                     match result {
                         Ok((len, _absender)) => {
-                            // Deaf? -> Paket verwerfen
-                            if deafened.load(Ordering::Relaxed) {
-                                continue;
-                            }
-
-                            // Pipeline gestoppt?
-                            if !running.load(Ordering::Relaxed) {
+                            // Ein Schnappschuss fuer das ganze Paket
+                            let zustand = *steuer_rx.borrow();
+                            if !zustand.gilt_fuer(lauf) {
                                 break;
                             }
 
                             // VoicePacket dekodieren
-                            let paket = match VoicePacket::decode(&buf[..len]) {
+                            let mut paket = match VoicePacket::decode(&buf[..len]) {
                                 Ok(p) => p,
                                 Err(e) => {
                                     trace!("Ungueltiges Voice-Paket: {}", e);
@@ -538,64 +1626,182 @@ impl VoiceClient {
                                 }
                             };
 
-                            // Silence-Pakete ueberspringen (kein Decode noetig)
-                            if paket.header.packet_type == speakeasy_protocol::voice::PacketType::Silence {
+                            // Erstes Paket vom Server: Endpunkt ist angemeldet
+                            if hello.take().is_some() {
+                                debug!("UDP-Endpunkt vom Server bestaetigt");
+                            }
+                            if paket.header.packet_type == PacketType::Hello {
                                 continue;
                             }
 
-                            // Opus Decode
-                            let pcm = match decoder.decode(&paket.payload) {
-                                Ok(samples) => samples,
-                                Err(e) => {
-                                    trace!("Opus-Decoding fehlgeschlagen: {}", e);
-                                    // PLC (Packet Loss Concealment)
-                                    match decoder.decode_plc() {
-                                        Ok(plc) => plc,
-                                        Err(_) => continue,
-                                    }
-                                }
-                            };
+                            pakete.empfangen_erfassen();
+                            voice_trace.aufzeichnen(
+                                Richtung::Empfangen,
+                                &paket.header,
+                                len,
+                                &paket.payload,
+                            );
+
+                            // Downlink-Statistik (auch wenn deaf – misst das Netz)
+                            if let Ok(mut stat) = statistik.lock() {
+                                stat.paket_empfangen(paket.header.ssrc, paket.header.sequence);
+                            }
 
-                            // In Playback-Ring-Buffer schreiben
-                            let written = playback_producer.push_slice(&pcm);
-                            if written < pcm.len() {
-                                trace!(
-                                    "Playback Ring-Buffer voll: {} von {} Samples geschrieben",
-                                    written,
-                                    pcm.len()
-                                );
+                            // Deaf? -> Paket verwerfen
+                            if zustand.deafened {
+                                continue;
                             }
+
+                            // E2E: vor dem Jitter-Puffer entschluesseln;
+                            // unbekannte Epoch, Manipulation oder Klartext -> verwerfen
+                            if let Some(schluessel) = &verschluesselung {
+                                let jetzt = std::time::Instant::now();
+                                if let Err(e) = schluessel.paket_entschluesseln(&mut paket, jetzt) {
+                                    trace!(ssrc = paket.header.ssrc, "Paket verworfen: {}", e);
+                                    continue;
+                                }
+                            }
+
+                            jitter.einreihen(paket);
                         }
-                        Err(e) => {
-                            if running.load(Ordering::Relaxed) {
-                                warn!("UDP-Empfangsfehler: {}", e);
+                        // Socket-Fehler beenden die Loop nie
+                        Err(e) => match udp_fehler::einordnen(&e) {
+                            UdpFehlerArt::ZielUnerreichbar => {
+                                unerreichbar += 1;
+                                trace!("ICMP-Rueckmeldung ignoriert ({}): {}", unerreichbar, e);
                             }
+                            UdpFehlerArt::Voruebergehend => {}
+                            UdpFehlerArt::Sonstiger => {
+                                if steuer_rx.borrow().gilt_fuer(lauf) {
+                                    warn!("UDP-Empfangsfehler: {}", e);
+                                }
+                                // Busy-Loop bei persistentem Fehler vermeiden
+                                tokio::time::sleep(std::time::Duration::from_millis(1)).await;
+                            }
+                        },
+                    }
+                }
+
+                // Abspieltakt: je Sprecher den naechsten Frame dekodieren,
+                // einen gemischten Frame ausgeben
+                _ = abspiel_takt.tick() => {
+                    let ausgabe = jitter.takt();
+                    if steuer_rx.borrow().deafened {
+                        wiedergabe.leeren();
+                        continue;
+                    }
+                    for abspielen in ausgabe {
+                        Self::abspielen(abspielen, &mut dekoder, &mut mischer, &mut wiedergabe);
+                    }
+                    let gemischt = wiedergabe.mischen(&mut ausgabe_frame);
+                    // Auch Stille: die Referenz laeuft im Takt der Ausgabe
+                    if let Some(referenz) = referenz.as_mut() {
+                        referenz.einspeisen(&ausgabe_frame);
+                    }
+                    if gemischt {
+                        let written = playback_producer.push_slice(&ausgabe_frame);
+                        if written < ausgabe_frame.len() {
+                            trace!(
+                                "Playback Ring-Buffer voll: {} von {} Samples geschrieben",
+                                written,
+                                ausgabe_frame.len()
+                            );
                         }
                     }
                 }
 
-                // Shutdown-Signal
-                _ = &mut shutdown_rx => {
-                    debug!("Empfangs-Loop: Shutdown-Signal empfangen");
-                    break;
+                _ = puffer_meldung.tick() => {
+                    if let Ok(mut stat) = statistik.lock() {
+                        stat.puffer_stand_setzen(jitter.stand());
+                        stat.wiedergabe_setzen(&wiedergabe.statistiken());
+                    }
+                    if jitter.instabil_melden() {
+                        let budget_ms = jitter.einstellung().budget_ms();
+                        info!(budget_ms, "Netzwerk zu instabil fuer das Latenzbudget");
+                        ereignis_melden(
+                            &dekoder.ereignisse,
+                            VoiceEreignis::NetworkUnstable { budget_ms },
+                        );
+                    }
+                }
+
+                // Stopp (oder VoiceClient gedroppt)
+                geaendert = steuer_rx.changed() => {
+                    if geaendert.is_err() || !steuer_rx.borrow_and_update().gilt_fuer(lauf) {
+                        debug!("Empfangs-Loop: Stopp empfangen");
+                        break;
+                    }
                 }
             }
         }
 
         debug!("Empfangs-Loop beendet");
     }
+
+    /// Dekodiert einen Frame aus dem Jitter-Puffer und reiht ihn in die
+    /// Warteschlange seines Sprechers ein
+    fn abspielen(
+        abspielen: Abspielen,
+        dekoder: &mut StreamDekoder,
+        mischer: &mut EmpfangsMischer,
+        wiedergabe: &mut WiedergabeMischer,
+    ) {
+        let (ssrc, mut pcm) = match abspielen {
+            // Fehlender Frame: PLC des Sprechers
+            Abspielen::Verdecken { ssrc } => match dekoder.verdecken(ssrc) {
+                Some(pcm) => (ssrc, pcm),
+                None => return,
+            },
+            Abspielen::Paket(paket) => {
+                // Silence-Pakete nicht dekodieren, aber als empfangen zaehlen
+                if paket.header.packet_type == speakeasy_protocol::voice::PacketType::Silence {
+                    dekoder.stille(paket.header.ssrc, paket.header.sequence);
+                    return;
+                }
+
+                // Warteschlange laeuft dauerhaft ueber: Frame waere ohnehin weg
+                if !wiedergabe.dekodieren_lohnt(paket.header.ssrc) {
+                    dekoder.ueberspringen(paket.header.ssrc, paket.header.sequence);
+                    return;
+                }
+
+                // Decoder des Sprechers (fehlt er, wird der Stream uebersprungen)
+                let codec = AudioCodec::von_header(&paket.header);
+                let Some(strom) = dekoder.fuer(paket.header.ssrc, codec) else {
+                    return;
+                };
+                // Einzelverluste per FEC oder PLC ausgleichen,
+                // verspaetete Pakete liefern nichts
+                match strom.dekodieren(&paket.header, &paket.payload) {
+                    Ok(samples) if !samples.is_empty() => (paket.header.ssrc, samples),
+                    Ok(_) => return,
+                    Err(e) => {
+                        trace!("Decoding fehlgeschlagen: {}", e);
+                        return;
+                    }
+                }
+            }
+        };
+
+        // Lautstaerke des Sprechers (lokal stumm -> verwerfen)
+        if !mischer.anwenden(ssrc, &mut pcm) {
+            return;
+        }
+
+        wiedergabe.einreihen(ssrc, &pcm);
+    }
 }
 
 impl Drop for VoiceClient {
     fn drop(&mut self) {
         // Sicherstellen dass alles gestoppt wird
-        self.running.store(false, Ordering::Relaxed);
-        if let Some(tx) = self.shutdown_tx.take() {
-            let _ = tx.send(());
+        self.steuerung.stoppen();
+        if let Some(handle) = self.recv_task.take() {
+            handle.abort();
         }
-        // Audio-Thread joinen (blockiert kurz, aber Drop ist synchron)
+        // Audio-Thread joinen (blockiert hoechstens STOP_FRIST, Drop ist synchron)
         if let Some(handle) = self.audio_thread.take() {
-            let _ = handle.join();
+            voice_steuerung::thread_joinen_blockierend(handle, STOP_FRIST);
         }
         debug!("VoiceClient gedroppt");
     }
@@ -605,6 +1811,71 @@ impl Drop for VoiceClient {
 // Hilfsfunktionen
 // ---------------------------------------------------------------------------
 
+/// Takt des Sende-Loops mit Discontinuous Transmission
+///
+/// Entscheidet je Frame, ob ein Paket gesendet wird, und fuehrt den
+/// Zeitstempel (in Samples) unabhaengig davon weiter.
+#[derive(Debug, Clone)]
+struct Dtx {
+    /// Jeder wievielte Stille-Frame gesendet wird (1 = jeder)
+    keepalive_frames: u32,
+    /// Stille-Frames seit dem letzten Sprach-Frame
+    stille: u32,
+    /// Zeitstempel des naechsten Frames
+    zeitstempel: u32,
+    frame_size: u32,
+}
+
+impl Dtx {
+    /// `keepalive` = Abstand der Silence-Pakete (`None` = jeden Frame senden)
+    fn neu(keepalive: Option<std::time::Duration>, frame_size: usize) -> Self {
+        let frame_ms = (frame_size as u64 * 1000 / SAMPLE_RATE as u64).max(1);
+        let keepalive_frames = keepalive
+            .map(|k| (k.as_millis() as u64 / frame_ms).clamp(1, u32::MAX as u64) as u32)
+            .unwrap_or(1);
+        Self {
+            keepalive_frames,
+            stille: 0,
+            zeitstempel: 0,
+            frame_size: frame_size as u32,
+        }
+    }
+
+    /// Schreibt den Takt um einen Frame fort
+    ///
+    /// Gibt den Zeitstempel des Frames zurueck und ob er gesendet werden
+    /// soll: Sprache immer, Stille nur der erste Frame nach dem Sprechen
+    /// und danach jeder `keepalive_frames`-te.
+    fn frame(&mut self, sprache: bool) -> (u32, bool) {
+        let zeitstempel = self.zeitstempel;
+        self.zeitstempel = self.zeitstempel.wrapping_add(self.frame_size);
+        if sprache {
+            self.stille = 0;
+            return (zeitstempel, true);
+        }
+        let senden = self.stille % self.keepalive_frames == 0;
+        self.stille = self.stille.wrapping_add(1);
+        (zeitstempel, senden)
+    }
+}
+
+/// Silence-Paket mit `SPEAKING_STOP`: beendet die Sprech-Anzeige beim Empfaenger
+fn stop_paket(sequence: u32, timestamp: u32, ssrc: u32) -> VoicePacket {
+    let mut paket = VoicePacket::neu_silence(sequence, timestamp, ssrc);
+    paket.header.flags |= VoiceFlags::SPEAKING_STOP;
+    paket
+}
+
+/// Haengt ein Ereignis an; die aeltesten fallen weg, wenn niemand abholt
+fn ereignis_melden(ereignisse: &Mutex<Vec<VoiceEreignis>>, ereignis: VoiceEreignis) {
+    if let Ok(mut ereignisse) = ereignisse.lock() {
+        if ereignisse.len() >= MAX_EREIGNISSE {
+            ereignisse.remove(0);
+        }
+        ereignisse.push(ereignis);
+    }
+}
+
 /// Berechnet den RMS-Pegel eines Audio-Frames
 fn rms_level(samples: &[f32]) -> f32 {
     if samples.is_empty() {
@@ -651,18 +1922,376 @@ mod tests {
     #[test]
     fn voice_client_mute_flags() {
         let client = VoiceClient::new();
-        assert!(!client.muted.load(Ordering::Relaxed));
+        assert!(!client.steuerung.zustand().muted);
         client.set_muted(true);
-        assert!(client.muted.load(Ordering::Relaxed));
+        assert!(client.steuerung.zustand().muted);
         client.set_muted(false);
-        assert!(!client.muted.load(Ordering::Relaxed));
+        assert!(!client.steuerung.zustand().muted);
+    }
+
+    #[test]
+    fn voice_client_dscp_standard_ef() {
+        let mut client = VoiceClient::new();
+        assert_eq!(client.dscp, Some(qos::DSCP_EF));
+        assert_eq!(client.qos_status(), &QosStatus::Deaktiviert);
+        client.set_dscp(None);
+        assert_eq!(client.dscp, None);
+    }
+
+    #[test]
+    fn effekt_ohne_pipeline_wird_verworfen() {
+        let client = VoiceClient::new();
+        assert!(!client.effekt_abspielen(&[0.1, 0.2]));
+    }
+
+    /// Konfiguration, mit der weder Opus-Encoder noch -Decoder erstellbar sind
+    fn kaputte_opus_config() -> OpusConfig {
+        let mut config = standard_opus_config();
+        config.bitrate_kbps = 0;
+        config
+    }
+
+    #[test]
+    fn ausgehandelte_opus_konfiguration_ersetzt_den_standard() {
+        let mut client = VoiceClient::new();
+        let mut accepted = AudioPreset::Speech.config();
+        accepted.bitrate_kbps = 20;
+        client.ausgehandelt_uebernehmen(&CodecNegotiationResponse {
+            status: NegotiationStatus::Adjusted,
+            accepted: accepted.clone(),
+            adjustment_reason: Some("Preset balanced nicht erlaubt".into()),
+            server_max_bitrate_kbps: 20,
+            preset: Some(AudioPreset::Speech),
+        });
+
+        assert_eq!(client.opus_config, accepted);
+        assert_eq!(client.bitrate_kbps(), 20);
+        // Adaptive Vorgaben gelten in den Grenzen des vereinbarten Presets
+        client.ziel_bitrate.store(200, Ordering::Relaxed);
+        assert_eq!(
+            client.bitrate_kbps(),
+            AudioPreset::Speech.bitrate_begrenzen(200)
+        );
+    }
+
+    #[tokio::test]
+    async fn opus_fehler_bricht_start_vorher_ab() {
+        let mut client = VoiceClient::new();
+        client.set_opus_config(kaputte_opus_config());
+
+        let fehler = client
+            .start("127.0.0.1:9987".parse().unwrap(), 7, AudioCodec::Opus, None)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            fehler,
+            VoiceStartFehler::CodecNichtVerfuegbar {
+                codec: AudioCodec::Opus,
+                ..
+            }
+        ));
+        assert!(fehler.benutzer_meldung().contains("PCM-Fallback"));
+        assert!(!client.is_running());
+        assert_eq!(client.ssrc(), 0);
+    }
+
+    #[tokio::test]
+    async fn hello_anmeldung_authentifiziert_und_gibt_auf() {
+        let server = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let client = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let nonce = vec![3u8; 32];
+        let mut anmeldung =
+            HelloAnmeldung::neu(server.local_addr().unwrap(), 0xCAFE, nonce.clone());
+
+        assert!(anmeldung.senden(&client).await);
+        let mut buf = [0u8; 64];
+        let (len, absender) = server.recv_from(&mut buf).await.unwrap();
+        assert_eq!(absender, client.local_addr().unwrap());
+        let hello = HelloPaket::decode(&buf[..len]).unwrap();
+        assert_eq!(hello.ssrc, 0xCAFE);
+        assert_eq!(hello.versuch, 0);
+        assert!(hello.pruefen(&nonce));
+
+        anmeldung.versuch = HELLO_MAX_VERSUCHE;
+        assert!(!anmeldung.senden(&client).await);
+    }
+
+    #[test]
+    fn ohne_opus_wird_pcmu_angefragt() {
+        assert_eq!(
+            VoiceClient::bevorzugter_codec(&kaputte_opus_config()),
+            AudioCodec::Pcmu
+        );
+    }
+
+    #[test]
+    fn decoder_fehler_ueberspringt_nur_diesen_stream() {
+        let ereignisse = Arc::new(Mutex::new(Vec::new()));
+        let zaehler = CodecZaehler::default();
+        let mut dekoder = StreamDekoder::neu(
+            kaputte_opus_config(),
+            Arc::clone(&ereignisse),
+            zaehler.clone(),
+        );
+
+        assert!(dekoder.fuer(7, AudioCodec::Opus).is_none());
+        assert!(dekoder.fuer(7, AudioCodec::Opus).is_none());
+        // Andere Streams laufen weiter
+        assert!(dekoder.fuer(8, AudioCodec::Pcmu).is_some());
+        let header = VoicePacketHeader::new(
+            speakeasy_protocol::voice::PacketType::Audio,
+            VoiceFlags::PCMU,
+            0,
+            0,
+            8,
+        );
+        assert!(dekoder
+            .fuer(8, AudioCodec::Pcmu)
+            .unwrap()
+            .dekodieren(&header, &[0xFF; 160])
+            .is_ok());
+
+        let ereignisse = ereignisse.lock().unwrap();
+        assert_eq!(ereignisse.len(), 1, "Ereignis nur einmal pro Stream");
+        assert!(matches!(
+            &ereignisse[0],
+            VoiceEreignis::StreamUebersprungen { ssrc: 7, .. }
+        ));
+    }
+
+    #[test]
+    fn schneller_sprecher_laeuft_nur_in_eigener_warteschlange_ueber() {
+        let mut dekoder = StreamDekoder::neu(
+            standard_opus_config(),
+            Arc::new(Mutex::new(Vec::new())),
+            CodecZaehler::default(),
+        );
+        let mut mischer = EmpfangsMischer::neu(Arc::new(BenutzerPegel::neu()));
+        let mut wiedergabe = WiedergabeMischer::neu(MischerKonfig::default());
+        let paket = |ssrc: u32, seq: u32| {
+            let mut paket = VoicePacket::neu_audio(seq, seq * 960, ssrc, vec![0xFF; 160]);
+            paket.header.flags = VoiceFlags::PCMU;
+            Abspielen::Paket(paket)
+        };
+
+        let mut frame = vec![0.0f32; 960];
+        for takt in 0..100 {
+            // SSRC 7 liefert doppelt so schnell wie SSRC 8
+            for seq in [2 * takt, 2 * takt + 1] {
+                VoiceClient::abspielen(paket(7, seq), &mut dekoder, &mut mischer, &mut wiedergabe);
+            }
+            VoiceClient::abspielen(paket(8, takt), &mut dekoder, &mut mischer, &mut wiedergabe);
+            assert!(wiedergabe.mischen(&mut frame));
+        }
+
+        let schnell = wiedergabe.statistik(7).unwrap();
+        assert!(schnell.max_tiefe <= speakeasy_audio::mischer::STANDARD_MAX_FRAMES);
+        assert!(schnell.ueberlauf > 80);
+        assert!(schnell.nicht_dekodiert > 0);
+        let normal = wiedergabe.statistik(8).unwrap();
+        assert_eq!(normal.ueberlauf, 0);
+        assert_eq!(normal.tiefe, 0);
+    }
+
+    #[test]
+    fn ereignisse_werden_begrenzt_und_abgeholt() {
+        let client = VoiceClient::new();
+        for ssrc in 0..(MAX_EREIGNISSE as u32 + 5) {
+            ereignis_melden(
+                &client.ereignisse,
+                VoiceEreignis::StreamUebersprungen {
+                    ssrc,
+                    codec: "opus".into(),
+                    grund: String::new(),
+                },
+            );
+        }
+        let abgeholt = client.ereignisse_abholen();
+        assert_eq!(abgeholt.len(), MAX_EREIGNISSE);
+        assert!(matches!(
+            abgeholt[0],
+            VoiceEreignis::StreamUebersprungen { ssrc: 5, .. }
+        ));
+        assert!(client.ereignisse_abholen().is_empty());
+    }
+
+    #[test]
+    fn stop_paket_beendet_sprech_anzeige() {
+        let paket = VoicePacket::decode(&stop_paket(9, 9 * 960, 42).encode()).unwrap();
+        assert!(paket.spricht_stop());
+        assert!(!paket.spricht_start());
+        assert_eq!(paket.header.sequence, 9);
+        assert_eq!(paket.header.ssrc, 42);
+        assert!(paket.payload.is_empty());
+    }
+
+    #[test]
+    fn sprech_zustand_meldet_nur_wechsel() {
+        let meldungen = Arc::new(Mutex::new(Vec::new()));
+        let gemeldet = Arc::clone(&meldungen);
+        let zustand = SprechZustand {
+            spricht: Arc::new(AtomicBool::new(false)),
+            melder: Some(Arc::new(move |spricht| {
+                gemeldet.lock().unwrap().push(spricht)
+            })),
+        };
+        zustand.setzen(false);
+        zustand.setzen(true);
+        zustand.setzen(true);
+        zustand.setzen(false);
+        assert_eq!(*meldungen.lock().unwrap(), [true, false]);
+        assert!(!zustand.spricht.load(Ordering::Relaxed));
+    }
+
+    #[test]
+    fn hardware_stumm_ereignis_format() {
+        let json =
+            serde_json::to_value(VoiceEreignis::HardwareMuteDetected { muted: true }).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({ "typ": "hardware_mute_detected", "muted": true })
+        );
+    }
+
+    #[test]
+    fn dtx_sendet_in_stille_nur_keepalives() {
+        let mut dtx = Dtx::neu(Some(STANDARD_DTX_KEEPALIVE), FRAME_SIZE);
+
+        // Sprechen, dann 5 Sekunden Stille (250 Frames)
+        assert_eq!(dtx.frame(true), (0, true));
+        let gesendet: Vec<u32> = (0..250)
+            .filter_map(|_| match dtx.frame(false) {
+                (ts, true) => Some(ts),
+                (_, false) => None,
+            })
+            .collect();
+        // Stopp-Paket plus ein Keepalive alle 400 ms statt 250 Pakete
+        assert_eq!(gesendet.len(), 13);
+        assert_eq!(gesendet[0], FRAME_SIZE as u32);
+        assert_eq!(gesendet[1] - gesendet[0], 20 * FRAME_SIZE as u32);
+
+        // Zeitstempel ist waehrend der Pause weitergelaufen, Sprache geht
+        // sofort wieder raus
+        assert_eq!(dtx.frame(true), (251 * FRAME_SIZE as u32, true));
+        assert_eq!(dtx.frame(false), (252 * FRAME_SIZE as u32, true));
+    }
+
+    #[test]
+    fn dtx_aus_sendet_jeden_frame() {
+        let mut dtx = Dtx::neu(None, FRAME_SIZE);
+        assert!((0..250).all(|_| dtx.frame(false).1));
+        assert_eq!(dtx.frame(false).0, 250 * FRAME_SIZE as u32);
+    }
+
+    #[test]
+    fn stoergeraeusch_ereignis_format() {
+        let json =
+            serde_json::to_value(VoiceEreignis::BackgroundNoiseWarning { score: 0.75 }).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({ "typ": "background_noise_warning", "score": 0.75 })
+        );
+    }
+
+    #[test]
+    fn instabil_ereignis_format() {
+        let json = serde_json::to_value(VoiceEreignis::NetworkUnstable { budget_ms: 240 }).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({ "typ": "network_unstable", "budget_ms": 240 })
+        );
+    }
+
+    fn beitritt(user_id: UserId, ssrc: u32) -> speakeasy_protocol::control::ControlPayload {
+        use speakeasy_protocol::control::{ChannelJoinResponse, ClientInfo, ControlPayload};
+        ControlPayload::ChannelJoinResponse(ChannelJoinResponse {
+            channel_id: speakeasy_core::types::ChannelId::new(),
+            clients: vec![ClientInfo {
+                user_id,
+                username: "anna".into(),
+                display_name: "Anna".into(),
+                channel_id: None,
+                server_groups: vec![],
+                is_muted: false,
+                is_deafened: false,
+                is_input_muted: false,
+                ssrc: Some(ssrc),
+                listen_only: false,
+                soundboard: false,
+            }],
+            member_count: 2,
+            members_partial: false,
+            listen_only: false,
+            speaking: Vec::new(),
+            slow_mode_secs: 0,
+        })
+    }
+
+    #[test]
+    fn gespeicherte_lautstaerke_gilt_nach_reconnect_mit_neuer_ssrc() {
+        use speakeasy_protocol::ssrc::SsrcZuordnung;
+
+        let pegel = Arc::new(BenutzerPegel::neu());
+        let anna = UserId::new();
+
+        // Erste Sitzung: Anna unter SSRC 10 auf 40% gestellt
+        let mut zuordnung = SsrcZuordnung::neu();
+        zuordnung.anwenden(&beitritt(anna, 10));
+        pegel.zuordnung_setzen(&zuordnung);
+        pegel.lautstaerke_setzen(anna, 0.4, benutzer_audio::jetzt_unix());
+
+        // Trennen: Zuordnung weg, Einstellung bleibt (wie nach Export/Laden)
+        let gespeichert = pegel.einstellungen();
+        let pegel = Arc::new(BenutzerPegel::neu());
+        pegel.laden(gespeichert, benutzer_audio::jetzt_unix());
+
+        // Neue Sitzung, neue SSRC; vor der Zuordnung bleibt das Paket unveraendert
+        let mut mischer = EmpfangsMischer::neu(Arc::clone(&pegel));
+        let mut frame = vec![0.5f32; 4];
+        assert!(mischer.anwenden(20, &mut frame));
+        assert_eq!(frame, vec![0.5; 4]);
+
+        let mut zuordnung = SsrcZuordnung::neu();
+        zuordnung.anwenden(&beitritt(anna, 20));
+        pegel.zuordnung_setzen(&zuordnung);
+
+        // Erstes zugeordnetes Paket: sofort 40%, ohne Einblendung
+        let mut frame = vec![0.5f32; 4];
+        assert!(mischer.anwenden(20, &mut frame));
+        assert!(frame.iter().all(|s| (s - 0.2).abs() < 1e-6), "{frame:?}");
+
+        // Lokal stumm: Pakete werden verworfen
+        pegel.stumm_setzen(anna, true, 0);
+        assert!(!mischer.anwenden(20, &mut vec![0.5f32; 4]));
     }
 
     #[test]
     fn voice_client_deafen_impliziert_mute() {
         let client = VoiceClient::new();
         client.set_deafened(true);
-        assert!(client.deafened.load(Ordering::Relaxed));
-        assert!(client.muted.load(Ordering::Relaxed));
+        let zustand = client.steuerung.zustand();
+        assert!(zustand.deafened);
+        assert!(zustand.senden_gesperrt());
+
+        // Undeafen stellt den vorherigen Mute-Zustand wieder her
+        client.set_deafened(false);
+        assert!(!client.steuerung.zustand().senden_gesperrt());
+    }
+
+    #[tokio::test]
+    async fn gescheiterter_start_laesst_neuen_start_zu() {
+        let mut client = VoiceClient::new();
+        client.set_opus_config(kaputte_opus_config());
+        for _ in 0..10 {
+            assert!(client
+                .start("127.0.0.1:9987".parse().unwrap(), 7, AudioCodec::Opus, None)
+                .await
+                .is_err());
+            assert!(!client.is_running());
+            assert!(client.audio_thread.is_none());
+            assert!(client.recv_task.is_none());
+        }
+        // Stop ohne laufende Pipeline ist ein No-op
+        client.stop().await;
     }
 }
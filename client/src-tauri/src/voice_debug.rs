@@ -0,0 +1,257 @@
+//! Voice-Debugzustand – Momentaufnahme der Pipeline fuer die Fehlersuche
+//!
+//! Fasst fuer den Support zusammen, was die Pipeline gerade tut: Geraete und
+//! Raten der Engine-Konfiguration, die zuletzt uebernommenen Audio-
+//! Einstellungen (DSP-Stufen, Codec, Jitter), den Steuer-Zustand und die
+//! ausgehandelte Sitzung des [`VoiceClient`](crate::voice::VoiceClient)
+//! sowie dessen Zaehler.
+//!
+//! Gelesen werden nur geteilte Atomics und Kopien der Konfiguration; auf
+//! die Verlust-Statistik des Empfangs-Loops wird nicht gewartet
+//! (`try_lock`). Vor der Ausgabe entfernt [`schwaerzen`] alles, was nach
+//! Zugangsdaten aussieht.
+
+use serde::Serialize;
+use speakeasy_audio::engine::AudioEngineConfig;
+use speakeasy_protocol::codec::OpusConfig;
+use speakeasy_protocol::control::VoiceReadyResponse;
+use speakeasy_protocol::qos::QosStatus;
+
+use crate::commands::{default_audio_settings, AudioSettingsConfig, CodecErrorStats};
+use crate::state::AudioState;
+
+/// Ersatzwert fuer geschwaerzte Felder
+pub const GESCHWAERZT: &str = "[redacted]";
+
+/// Namensbestandteile von Feldern, die nie im Klartext ausgegeben werden
+const GEHEIME_FELDER: &[&str] = &["token", "password", "passwort", "secret"];
+
+/// Beim Voice-Init ausgehandelte Sitzung (aus `VoiceReady`)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VoiceSitzung {
+    /// Transportweg der Sprachpakete
+    pub transport: String,
+    /// Krypto-Modus des Servers (`none`, `dtls`, `e2e`)
+    pub crypto_mode: String,
+    /// Server hat einen DTLS-Fingerprint geschickt
+    pub dtls_fingerprint: bool,
+    /// Server schreibt Sequenznummern weitergeleiteter Pakete um
+    pub resequencing: bool,
+}
+
+impl VoiceSitzung {
+    pub fn aus_voice_ready(ready: &VoiceReadyResponse) -> Self {
+        Self {
+            transport: "udp".to_string(),
+            crypto_mode: ready.crypto_mode.clone(),
+            dtls_fingerprint: ready.server_dtls_fingerprint.is_some(),
+            resequencing: ready.resequencing,
+        }
+    }
+}
+
+/// Geraete und Puffer der Audio-Engine
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EngineDebug {
+    pub input_device: Option<String>,
+    pub output_device: Option<String>,
+    pub capture_sample_rate: u32,
+    pub capture_channels: u16,
+    pub capture_buffer_size: usize,
+    pub playback_sample_rate: u32,
+    pub playback_channels: u16,
+    pub playback_buffer_size: usize,
+    pub ptt_mode: String,
+    pub minimal_pipeline: bool,
+}
+
+impl From<&AudioEngineConfig> for EngineDebug {
+    fn from(c: &AudioEngineConfig) -> Self {
+        Self {
+            input_device: c.input_device.clone(),
+            output_device: c.output_device.clone(),
+            capture_sample_rate: c.capture.sample_rate,
+            capture_channels: c.capture.channels,
+            capture_buffer_size: c.capture.buffer_size,
+            playback_sample_rate: c.playback.sample_rate,
+            playback_channels: c.playback.channels,
+            playback_buffer_size: c.playback.buffer_size,
+            ptt_mode: format!("{:?}", c.ptt_mode),
+            minimal_pipeline: c.minimal_pipeline,
+        }
+    }
+}
+
+/// Jitter-Puffer des Empfangspfads (in Frames)
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JitterDebug {
+    pub min_frames: usize,
+    pub max_frames: usize,
+    pub budget_frames: usize,
+    pub adaptive: bool,
+}
+
+/// Zaehler der laufenden bzw. letzten Sitzung
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PipelineZaehler {
+    pub highest_sequence: Option<u32>,
+    pub playback_underruns: u64,
+    pub codec_errors: CodecErrorStats,
+    /// Verlust in Prozent (`None` = Statistik gerade in Benutzung)
+    pub uplink_loss: Option<f32>,
+    pub downlink_loss: Option<f32>,
+}
+
+/// Zustand des Voice-Clients
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PipelineDebug {
+    pub running: bool,
+    pub muted: bool,
+    pub deafened: bool,
+    pub listen_only: bool,
+    pub emergency_muted: bool,
+    pub speaking: bool,
+    pub ssrc: u32,
+    pub server_addr: String,
+    pub codec: String,
+    pub opus: OpusConfig,
+    /// Aktuelle Sende-Bitrate (Vorgabe des Servers, begrenzt auf das Preset)
+    pub bitrate_kbps: u16,
+    pub jitter: JitterDebug,
+    /// Abstand der Silence-Pakete (`None` = DTX aus)
+    pub dtx_keepalive_ms: Option<u64>,
+    /// Null-Signal-Dauer der Hardware-Stumm-Erkennung (`None` = aus)
+    pub hardware_mute_ms: Option<u64>,
+    pub voice_qos: QosStatus,
+    /// Ausgehandelte Sitzung (`None` = noch kein Voice-Init)
+    pub session: Option<VoiceSitzung>,
+    pub counters: PipelineZaehler,
+}
+
+/// Momentaufnahme fuer `get_voice_debug_state`
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VoiceDebugZustand {
+    pub engine: EngineDebug,
+    /// Zuletzt uebernommene Audio-Einstellungen (sonst die Standardwerte)
+    pub settings: AudioSettingsConfig,
+    /// `None` = kein Voice-Client (nicht in einem Kanal)
+    pub pipeline: Option<PipelineDebug>,
+}
+
+impl VoiceDebugZustand {
+    pub fn zusammenstellen(audio: &AudioState, pipeline: Option<PipelineDebug>) -> Self {
+        let engine = match &audio.engine_config {
+            Some(config) => EngineDebug::from(config),
+            None => EngineDebug::from(&AudioEngineConfig::default()),
+        };
+        Self {
+            engine,
+            settings: audio
+                .full_settings
+                .clone()
+                .unwrap_or_else(default_audio_settings),
+            pipeline,
+        }
+    }
+}
+
+/// Ersetzt rekursiv alle Werte, deren Feldname nach Zugangsdaten aussieht
+pub fn schwaerzen(wert: &mut serde_json::Value) {
+    match wert {
+        serde_json::Value::Object(felder) => {
+            for (name, feld) in felder.iter_mut() {
+                let name = name.to_lowercase();
+                if GEHEIME_FELDER.iter().any(|g| name.contains(g)) {
+                    if !feld.is_null() {
+                        *feld = serde_json::Value::String(GESCHWAERZT.to_string());
+                    }
+                } else {
+                    schwaerzen(feld);
+                }
+            }
+        }
+        serde_json::Value::Array(eintraege) => eintraege.iter_mut().for_each(schwaerzen),
+        _ => {}
+    }
+}
+
+/// Serialisiert einen Wert und schwaerzt ihn
+pub fn geschwaerzt<T: Serialize>(wert: &T) -> Result<serde_json::Value, String> {
+    let mut json = serde_json::to_value(wert).map_err(|e| e.to_string())?;
+    schwaerzen(&mut json);
+    Ok(json)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::rauschunterdrueckung_verstaerken;
+    use crate::voice::VoiceClient;
+    use crate::voice_jitter::JitterEinstellung;
+
+    #[test]
+    fn aenderung_der_rauschunterdrueckung_sofort_sichtbar() {
+        let mut audio = AudioState::default();
+        let vorher = geschwaerzt(&VoiceDebugZustand::zusammenstellen(&audio, None)).unwrap();
+        assert_eq!(
+            vorher["settings"]["dsp"]["noiseSuppression"]["level"],
+            "medium"
+        );
+
+        let stufe = rauschunterdrueckung_verstaerken(
+            audio
+                .full_settings
+                .get_or_insert_with(default_audio_settings),
+        );
+        assert_eq!(stufe, "high");
+
+        let nachher = geschwaerzt(&VoiceDebugZustand::zusammenstellen(&audio, None)).unwrap();
+        assert_eq!(
+            nachher["settings"]["dsp"]["noiseSuppression"]["level"],
+            "high"
+        );
+        assert_eq!(nachher["settings"]["noiseSuppression"], "high");
+    }
+
+    #[test]
+    fn pipeline_zeigt_steuerung_und_naechste_konfiguration() {
+        let mut client = VoiceClient::new();
+        client.set_muted(true);
+        client.set_jitter(JitterEinstellung::aus_ms(40, 120, false));
+        client.set_dtx(None);
+
+        let debug = client.debug_zustand();
+        assert!(!debug.running);
+        assert!(debug.muted);
+        assert!(!debug.jitter.adaptive);
+        assert_eq!(debug.jitter.budget_frames, 6);
+        assert_eq!(debug.dtx_keepalive_ms, None);
+        assert_eq!(debug.session, None);
+        assert_eq!(debug.counters.highest_sequence, None);
+    }
+
+    #[test]
+    fn zugangsdaten_werden_geschwaerzt() {
+        let mut wert = serde_json::json!({
+            "server": "voice.example.org",
+            "sessionToken": "abc",
+            "verbindung": { "serverPassword": "geheim", "port": 9987 },
+            "liste": [{ "apiSecret": "x" }],
+            "refreshToken": null,
+        });
+        schwaerzen(&mut wert);
+
+        assert_eq!(wert["server"], "voice.example.org");
+        assert_eq!(wert["sessionToken"], GESCHWAERZT);
+        assert_eq!(wert["verbindung"]["serverPassword"], GESCHWAERZT);
+        assert_eq!(wert["verbindung"]["port"], 9987);
+        assert_eq!(wert["liste"][0]["apiSecret"], GESCHWAERZT);
+        assert!(wert["refreshToken"].is_null());
+    }
+}
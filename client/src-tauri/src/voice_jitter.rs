@@ -0,0 +1,544 @@
+//! Jitter-Puffer im Empfangspfad
+//!
+//! Eingehende Pakete landen je Sprecher (SSRC) in einem
+//! [`AdaptiveJitterBuffer`] und werden im 20ms-Takt in Sequenz-Reihenfolge
+//! abgespielt, statt in der Reihenfolge, in der UDP sie zustellt.
+//!
+//! - Ein Sprecher beginnt erst, wenn sein Puffer die Zielgroesse erreicht;
+//!   die Zielgroesse folgt dem gemessenen Jitter zwischen Minimum und Maximum
+//! - Fehlt der naechste Frame zum Abspielzeitpunkt und liegt der Puffer
+//!   unter dem Ziel, wird ein PLC-Frame eingeschoben (hoechstens
+//!   [`MAX_VERDECKT`] am Stueck) und auf das Paket gewartet; der Puffer waechst
+//!   dabei um diesen Frame. Bleibt der Puffer leer, gilt der Sprecher danach
+//!   als pausiert und puffert neu vor
+//! - Liegen mehr Pakete als noetig im Puffer, werden Silence-Pakete im selben
+//!   Takt nachgeholt; das baut Latenz ohne hoerbaren Verlust ab
+//! - Mehr als das Latenzbudget (hoechstens das Maximum) haelt der Puffer
+//!   nie, die zusaetzliche Latenz ist damit durch `latency_budget` bzw.
+//!   `max_buffer` begrenzt. Das Ziel folgt dem Jitter nur bis zum Budget;
+//!   was darueber hinaus ankommt, wird verworfen (gezaehlt in [`PufferStand`])
+//!   und per PLC verdeckt. Verlangt der Jitter dauerhaft mehr, meldet
+//!   [`EmpfangsPuffer::instabil_melden`] das einmal
+//! - Pakete, deren Frame schon abgespielt oder uebersprungen wurde, kommen zu
+//!   spaet; [`PufferStand`] zaehlt sie fuer die Qualitaetsschaetzung
+
+use speakeasy_protocol::voice::{PacketType, VoicePacket};
+use speakeasy_voice::jitter_buffer::{AdaptiveJitterBuffer, JitterBufferConfig, JitterBufferModus};
+use std::collections::HashMap;
+use std::time::Duration;
+
+/// Abspieltakt (ein Frame)
+pub const TAKT: Duration = Duration::from_millis(20);
+
+/// So viele fehlende Frames am Stueck werden hoechstens verdeckt
+pub const MAX_VERDECKT: u32 = 3;
+
+/// Fenster der Jitter-Messung (Pakete)
+const JITTER_FENSTER: usize = 16;
+
+/// Standard-Latenzbudget des Jitter-Puffers (ms)
+pub const STANDARD_BUDGET_MS: u32 = 250;
+
+/// Groesse des Jitter-Puffers in Frames
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct JitterEinstellung {
+    /// Mindestens vorgehaltene Frames (im festen Modus die Zielgroesse)
+    pub min_pakete: usize,
+    /// Hoechstens gepufferte Frames
+    pub max_pakete: usize,
+    /// Latenzbudget in Frames (zwischen `min_pakete` und `max_pakete`)
+    pub budget_pakete: usize,
+    /// Zielgroesse am gemessenen Jitter ausrichten
+    pub adaptiv: bool,
+}
+
+impl Default for JitterEinstellung {
+    fn default() -> Self {
+        Self::aus_ms(20, 200, true).mit_budget_ms(STANDARD_BUDGET_MS)
+    }
+}
+
+impl JitterEinstellung {
+    /// Rechnet Millisekunden in Frames um (aufgerundet, mindestens ein Frame)
+    ///
+    /// Das Latenzbudget ist zunaechst das Maximum.
+    pub fn aus_ms(min_ms: u32, max_ms: u32, adaptiv: bool) -> Self {
+        let frame_ms = TAKT.as_millis() as u32;
+        let min_pakete = min_ms.div_ceil(frame_ms).max(1) as usize;
+        let max_pakete = (max_ms.div_ceil(frame_ms) as usize).max(min_pakete);
+        Self {
+            min_pakete,
+            max_pakete,
+            budget_pakete: max_pakete,
+            adaptiv,
+        }
+    }
+
+    /// Setzt das Latenzbudget (abgerundet, zwischen Minimum und Maximum)
+    pub fn mit_budget_ms(mut self, budget_ms: u32) -> Self {
+        let frames = (budget_ms / TAKT.as_millis() as u32) as usize;
+        self.budget_pakete = frames.clamp(self.min_pakete, self.max_pakete);
+        self
+    }
+
+    /// Latenzbudget in Millisekunden
+    pub fn budget_ms(&self) -> u32 {
+        self.budget_pakete as u32 * TAKT.as_millis() as u32
+    }
+}
+
+/// Zaehlerstand aller Sprecher-Puffer
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PufferStand {
+    /// Eingereihte Pakete (kumuliert)
+    pub eingereiht: u64,
+    /// Davon zu spaet fuer ihren Abspielzeitpunkt (kumuliert)
+    pub verspaetet: u64,
+    /// Davon wegen des Latenzbudgets verworfen (kumuliert)
+    pub verworfen: u64,
+    /// Zielgroesse des tiefsten Sprecher-Puffers (ms)
+    pub tiefe_ms: u32,
+}
+
+/// Was im aktuellen Takt abgespielt wird
+#[derive(Debug)]
+pub enum Abspielen {
+    /// Naechstes Paket eines Sprechers (Audio oder Silence)
+    Paket(VoicePacket),
+    /// Frame fehlt noch: Decoder schiebt einen PLC-Frame ein
+    Verdecken { ssrc: u32 },
+}
+
+/// Jitter-Puffer eines Sprechers
+struct SprecherPuffer {
+    puffer: AdaptiveJitterBuffer,
+    einstellung: JitterEinstellung,
+    /// Naechste erwartete Sequenz (fuer die Lueckenerkennung)
+    naechste: Option<u32>,
+    /// Vorpuffern abgeschlossen
+    spielt: bool,
+    /// Zuletzt abgespielt war Sprache (nur dann wird verdeckt)
+    letzte_audio: bool,
+    /// Am Stueck verdeckte Frames
+    verdeckt: u32,
+}
+
+impl SprecherPuffer {
+    fn neu(einstellung: JitterEinstellung) -> Self {
+        // Das Vorpuffern regelt dieser Puffer selbst, der innere gibt immer aus
+        let config = JitterBufferConfig {
+            modus: JitterBufferModus::Adaptiv,
+            max_pakete: einstellung.max_pakete,
+            min_pakete: 0,
+            jitter_fenster: JITTER_FENSTER,
+            budget_pakete: Some(einstellung.budget_pakete),
+        };
+        Self {
+            puffer: AdaptiveJitterBuffer::neu(config),
+            einstellung,
+            naechste: None,
+            spielt: false,
+            letzte_audio: false,
+            verdeckt: 0,
+        }
+    }
+
+    /// Zielgroesse in Frames
+    fn ziel(&self) -> usize {
+        if self.einstellung.adaptiv {
+            self.puffer
+                .ziel_groesse()
+                .clamp(self.einstellung.min_pakete, self.einstellung.budget_pakete)
+        } else {
+            self.einstellung.min_pakete
+        }
+    }
+
+    fn takt(&mut self, ssrc: u32, ausgabe: &mut Vec<Abspielen>) {
+        if !self.spielt {
+            if self.puffer.fuellstand() < self.ziel() {
+                return;
+            }
+            self.spielt = true;
+        }
+
+        loop {
+            let luecke = match (self.puffer.naechste_sequenz(), self.naechste) {
+                (None, _) => true,
+                (Some(seq), Some(naechste)) => seq != naechste,
+                (Some(_), None) => false,
+            };
+            // Fehlt der naechste Frame, kommt er vielleicht noch: solange der
+            // Puffer unter dem Ziel liegt, wird gewartet und der Puffer waechst
+            // um den eingeschobenen Frame. Sonst gilt er als verloren.
+            let warten = luecke && self.puffer.fuellstand() < self.ziel();
+            if warten || self.puffer.fuellstand() == 0 {
+                if self.letzte_audio && self.verdeckt < MAX_VERDECKT {
+                    self.verdeckt += 1;
+                    ausgabe.push(Abspielen::Verdecken { ssrc });
+                    return;
+                }
+                if self.puffer.fuellstand() == 0 {
+                    // Sprecher pausiert: beim naechsten Paket neu vorpuffern
+                    self.spielt = false;
+                    self.letzte_audio = false;
+                    self.verdeckt = 0;
+                    return;
+                }
+            }
+
+            let Some(paket) = self.puffer.pop() else {
+                return;
+            };
+            self.naechste = Some(paket.header.sequence.wrapping_add(1));
+            self.verdeckt = 0;
+            let stille = paket.header.packet_type == PacketType::Silence;
+            self.letzte_audio = !stille;
+            ausgabe.push(Abspielen::Paket(paket));
+
+            if !stille || self.puffer.fuellstand() <= self.ziel() {
+                return;
+            }
+        }
+    }
+
+    /// Kommt das Paket nach seinem Abspielzeitpunkt?
+    fn verspaetet(&self, sequenz: u32) -> bool {
+        self.naechste.is_some_and(|naechste| {
+            let abstand = naechste.wrapping_sub(sequenz);
+            abstand != 0 && abstand < u32::MAX / 2
+        })
+    }
+
+    /// Hat der Sprecher noch etwas abzuspielen?
+    fn aktiv(&self) -> bool {
+        self.spielt || self.puffer.fuellstand() > 0
+    }
+}
+
+/// Jitter-Puffer aller Sprecher
+pub struct EmpfangsPuffer {
+    einstellung: JitterEinstellung,
+    sprecher: HashMap<u32, SprecherPuffer>,
+    eingereiht: u64,
+    verspaetet: u64,
+    verworfen: u64,
+    /// Zuletzt gemeldet: mindestens ein Sprecher braucht mehr als das Budget
+    instabil: bool,
+}
+
+impl EmpfangsPuffer {
+    pub fn neu(einstellung: JitterEinstellung) -> Self {
+        Self {
+            einstellung,
+            sprecher: HashMap::new(),
+            eingereiht: 0,
+            verspaetet: 0,
+            verworfen: 0,
+            instabil: false,
+        }
+    }
+
+    /// Reiht ein empfangenes Paket beim Sprecher ein
+    pub fn einreihen(&mut self, paket: VoicePacket) {
+        let einstellung = self.einstellung;
+        let sprecher = self
+            .sprecher
+            .entry(paket.header.ssrc)
+            .or_insert_with(|| SprecherPuffer::neu(einstellung));
+        self.eingereiht += 1;
+        if sprecher.verspaetet(paket.header.sequence) {
+            self.verspaetet += 1;
+        }
+        let vorher = sprecher.puffer.statistik().budget_verworfen;
+        sprecher.puffer.push(paket);
+        self.verworfen += sprecher.puffer.statistik().budget_verworfen - vorher;
+    }
+
+    /// Ein Abspieltakt: je Sprecher der naechste Frame (oder nichts)
+    ///
+    /// Pausierte Sprecher ohne gepufferte Pakete werden vergessen.
+    pub fn takt(&mut self) -> Vec<Abspielen> {
+        let mut ausgabe = Vec::new();
+        self.sprecher.retain(|&ssrc, sprecher| {
+            sprecher.takt(ssrc, &mut ausgabe);
+            sprecher.aktiv()
+        });
+        ausgabe
+    }
+
+    /// Aktueller Zaehlerstand
+    pub fn stand(&self) -> PufferStand {
+        let frames = self.sprecher.values().map(SprecherPuffer::ziel).max();
+        PufferStand {
+            eingereiht: self.eingereiht,
+            verspaetet: self.verspaetet,
+            verworfen: self.verworfen,
+            tiefe_ms: frames.map_or(0, |f| f as u32 * TAKT.as_millis() as u32),
+        }
+    }
+
+    /// Ist das Netz zu instabil fuer das Latenzbudget?
+    ///
+    /// Gibt nur beim Wechsel in diesen Zustand `true` zurueck; erneut erst,
+    /// nachdem wieder alle Sprecher mit dem Budget auskamen.
+    pub fn instabil_melden(&mut self) -> bool {
+        let instabil = self.sprecher.values().any(|s| s.puffer.ueber_budget());
+        let melden = instabil && !self.instabil;
+        self.instabil = instabil;
+        melden
+    }
+
+    /// Puffergroesse aller Sprecher
+    pub fn einstellung(&self) -> JitterEinstellung {
+        self.einstellung
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use speakeasy_protocol::voice::VoicePacketHeader;
+
+    fn paket(seq: u32) -> VoicePacket {
+        VoicePacket {
+            header: VoicePacketHeader::new(PacketType::Audio, 0, seq, seq * 960, 7),
+            payload: vec![0xAB; 40],
+        }
+    }
+
+    fn stille(seq: u32) -> VoicePacket {
+        VoicePacket::neu_silence(seq, seq * 960, 7)
+    }
+
+    /// Sequenzen je Takt; Verdecken als `None`
+    fn abspielen(puffer: &mut EmpfangsPuffer) -> Vec<Option<u32>> {
+        puffer
+            .takt()
+            .into_iter()
+            .map(|a| match a {
+                Abspielen::Paket(p) => Some(p.header.sequence),
+                Abspielen::Verdecken { .. } => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn einstellung_aus_ms() {
+        assert_eq!(
+            JitterEinstellung::aus_ms(20, 200, true),
+            JitterEinstellung {
+                min_pakete: 1,
+                max_pakete: 10,
+                budget_pakete: 10,
+                adaptiv: true
+            }
+        );
+        let e = JitterEinstellung::aus_ms(0, 30, false);
+        assert_eq!((e.min_pakete, e.max_pakete), (1, 2));
+        let e = JitterEinstellung::aus_ms(100, 40, true);
+        assert_eq!((e.min_pakete, e.max_pakete), (5, 5));
+    }
+
+    #[test]
+    fn einstellung_mit_budget() {
+        let e = JitterEinstellung::aus_ms(40, 500, true);
+        assert_eq!(e.mit_budget_ms(250).budget_pakete, 12);
+        assert_eq!(e.mit_budget_ms(250).budget_ms(), 240);
+        // Nie unter dem Minimum, nie ueber dem Maximum
+        assert_eq!(e.mit_budget_ms(10).budget_pakete, 2);
+        assert_eq!(e.mit_budget_ms(5000).budget_pakete, 25);
+        assert_eq!(JitterEinstellung::default().budget_pakete, 10);
+    }
+
+    /// 200 Pakete im Takt, jeder zweite Viererblock kommt `verzoegerung`
+    /// Takte spaeter an; gibt die groesste Wartezeit eines abgespielten
+    /// Pakets in Takten zurueck
+    fn mit_jitter(puffer: &mut EmpfangsPuffer, verzoegerung: u32) -> usize {
+        let ankunft = |seq: u32| seq + verzoegerung * (seq / 4 % 2);
+        let mut max_latenz = 0;
+        for takt in 0..200 {
+            for seq in (0..200).filter(|&seq| ankunft(seq) == takt) {
+                puffer.einreihen(paket(seq));
+            }
+            for seq in abspielen(puffer).into_iter().flatten() {
+                max_latenz = max_latenz.max((takt - ankunft(seq)) as usize);
+            }
+        }
+        max_latenz
+    }
+
+    #[test]
+    fn starker_jitter_sprengt_das_budget_nicht() {
+        let einstellung = JitterEinstellung::aus_ms(20, 500, true).mit_budget_ms(100);
+        // Ohne Budget wuechse der Puffer deutlich darueber
+        let mut ohne_budget = EmpfangsPuffer::neu(JitterEinstellung::aus_ms(20, 500, true));
+        mit_jitter(&mut ohne_budget, 12);
+        assert!(ohne_budget.stand().tiefe_ms > 2 * einstellung.budget_ms());
+
+        let mut puffer = EmpfangsPuffer::neu(einstellung);
+        let max_latenz = mit_jitter(&mut puffer, 12);
+
+        // Hoechstens ein Budget voll Pakete plus die verdeckten Frames davor
+        assert!(
+            max_latenz < einstellung.budget_pakete + MAX_VERDECKT as usize,
+            "{max_latenz} Takte Latenz"
+        );
+        // Was nicht ins Budget passt, verpasst seinen Abspielzeitpunkt
+        let stand = puffer.stand();
+        assert!(stand.verspaetet > 0, "{stand:?}");
+        assert!(stand.tiefe_ms <= einstellung.budget_ms(), "{stand:?}");
+        // Einmal melden, nicht bei jeder Pruefung
+        assert!(puffer.instabil_melden());
+        assert!(!puffer.instabil_melden());
+    }
+
+    #[test]
+    fn geringer_jitter_bleibt_im_budget() {
+        let einstellung = JitterEinstellung::aus_ms(20, 500, true).mit_budget_ms(200);
+        let mut puffer = EmpfangsPuffer::neu(einstellung);
+        mit_jitter(&mut puffer, 2);
+
+        let stand = puffer.stand();
+        assert_eq!((stand.verspaetet, stand.verworfen), (0, 0), "{stand:?}");
+        assert!(stand.tiefe_ms < einstellung.budget_ms(), "{stand:?}");
+        assert!(!puffer.instabil_melden());
+    }
+
+    #[test]
+    fn schwall_ueber_dem_budget_wird_verworfen() {
+        let einstellung = JitterEinstellung::aus_ms(20, 500, false).mit_budget_ms(100);
+        let mut puffer = EmpfangsPuffer::neu(einstellung);
+        // Nach einem Haenger kommen 20 Pakete auf einmal
+        for seq in 0..20 {
+            puffer.einreihen(paket(seq));
+        }
+
+        assert_eq!(puffer.stand().verworfen, 15);
+        assert_eq!(abspielen(&mut puffer), vec![Some(15)]);
+    }
+
+    #[test]
+    fn vertauschte_pakete_in_reihenfolge_mit_begrenzter_latenz() {
+        let einstellung = JitterEinstellung::default();
+        let mut puffer = EmpfangsPuffer::neu(einstellung);
+        // Bloecke von vier Paketen kommen durcheinander an
+        const MUSTER: [u32; 4] = [2, 0, 3, 1];
+        let ankunft: Vec<u32> = (0..200u32)
+            .map(|i| i / 4 * 4 + MUSTER[i as usize % 4])
+            .collect();
+
+        let mut angekommen = HashMap::new();
+        let mut gespielt = Vec::new();
+        let mut max_latenz = 0;
+        for takt in 0..260usize {
+            if let Some(&seq) = ankunft.get(takt) {
+                angekommen.insert(seq, takt);
+                puffer.einreihen(paket(seq));
+            }
+            for seq in abspielen(&mut puffer).into_iter().flatten() {
+                max_latenz = max_latenz.max(takt - angekommen[&seq]);
+                gespielt.push(seq);
+            }
+        }
+
+        // Nach dem Einschwingen geht kein Paket mehr verloren
+        assert_eq!(gespielt, (0..200).collect::<Vec<_>>());
+        assert!(
+            max_latenz <= einstellung.max_pakete,
+            "{max_latenz} Takte Latenz"
+        );
+    }
+
+    #[test]
+    fn verspaeteter_frame_wird_verdeckt_und_nachgeholt() {
+        let mut puffer = EmpfangsPuffer::neu(JitterEinstellung::aus_ms(20, 200, false));
+        puffer.einreihen(paket(0));
+        assert_eq!(abspielen(&mut puffer), vec![Some(0)]);
+        // Sequenz 1 fehlt zum Abspielzeitpunkt
+        assert_eq!(abspielen(&mut puffer), vec![None]);
+        // Kommt sie mit 2 zusammen an, wird sie noch abgespielt
+        puffer.einreihen(paket(2));
+        puffer.einreihen(paket(1));
+        assert_eq!(abspielen(&mut puffer), vec![Some(1)]);
+        assert_eq!(abspielen(&mut puffer), vec![Some(2)]);
+    }
+
+    #[test]
+    fn luecke_bei_vollem_puffer_gilt_als_verlust() {
+        let mut puffer = EmpfangsPuffer::neu(JitterEinstellung::aus_ms(20, 200, false));
+        puffer.einreihen(paket(0));
+        assert_eq!(abspielen(&mut puffer), vec![Some(0)]);
+        // Sequenz 1 ging verloren, 2 liegt bereit: nicht warten
+        puffer.einreihen(paket(2));
+        assert_eq!(abspielen(&mut puffer), vec![Some(2)]);
+    }
+
+    #[test]
+    fn zu_spaete_pakete_werden_gezaehlt() {
+        let mut puffer = EmpfangsPuffer::neu(JitterEinstellung::aus_ms(20, 200, false));
+        puffer.einreihen(paket(0));
+        abspielen(&mut puffer);
+        puffer.einreihen(paket(2));
+        assert_eq!(abspielen(&mut puffer), vec![Some(2)]);
+        // Sequenz 1 war schon uebersprungen
+        puffer.einreihen(paket(1));
+        puffer.einreihen(paket(3));
+
+        let stand = puffer.stand();
+        assert_eq!((stand.eingereiht, stand.verspaetet), (4, 1));
+        assert_eq!(stand.tiefe_ms, 20);
+    }
+
+    #[test]
+    fn pause_nach_verdecken_puffert_neu_vor() {
+        let mut puffer = EmpfangsPuffer::neu(JitterEinstellung::aus_ms(20, 200, false));
+        puffer.einreihen(paket(0));
+        abspielen(&mut puffer);
+        for _ in 0..MAX_VERDECKT {
+            assert_eq!(abspielen(&mut puffer), vec![None]);
+        }
+        // Danach gilt der Sprecher als pausiert, nichts wird mehr erfunden
+        assert!(abspielen(&mut puffer).is_empty());
+        assert!(abspielen(&mut puffer).is_empty());
+
+        puffer.einreihen(paket(10));
+        assert_eq!(abspielen(&mut puffer), vec![Some(10)]);
+    }
+
+    #[test]
+    fn nach_stille_wird_nicht_verdeckt() {
+        let mut puffer = EmpfangsPuffer::neu(JitterEinstellung::aus_ms(20, 200, false));
+        puffer.einreihen(stille(0));
+        assert_eq!(abspielen(&mut puffer), vec![Some(0)]);
+        assert!(abspielen(&mut puffer).is_empty());
+    }
+
+    #[test]
+    fn ueberzaehlige_stille_wird_nachgeholt() {
+        let mut puffer = EmpfangsPuffer::neu(JitterEinstellung::aus_ms(40, 200, false));
+        for seq in 0..6 {
+            puffer.einreihen(stille(seq));
+        }
+        puffer.einreihen(paket(6));
+        // Ziel sind zwei Frames: die Stille davor geht in einem Takt raus
+        assert_eq!(
+            abspielen(&mut puffer),
+            vec![Some(0), Some(1), Some(2), Some(3), Some(4)]
+        );
+        assert_eq!(abspielen(&mut puffer), vec![Some(5)]);
+        assert_eq!(abspielen(&mut puffer), vec![Some(6)]);
+    }
+
+    #[test]
+    fn sprecher_werden_getrennt_gepuffert() {
+        let mut puffer = EmpfangsPuffer::neu(JitterEinstellung::aus_ms(20, 200, false));
+        let mut anna = paket(5);
+        anna.header.ssrc = 1;
+        puffer.einreihen(anna);
+        puffer.einreihen(paket(0));
+        let mut gespielt = abspielen(&mut puffer);
+        gespielt.sort();
+        assert_eq!(gespielt, vec![Some(0), Some(5)]);
+    }
+}
@@ -0,0 +1,386 @@
+//! E2E-Verschluesselung der Voice-Pakete
+//!
+//! Meldet der Server in `VoiceReady` den Modus `e2e`, verschluesselt der
+//! Sende-Loop jedes Audio-Paket mit dem aktuellen Gruppenschluessel des
+//! Kanals (Layout von `EncryptedPayload`: Nonce mit Epoch, AAD mit SSRC und
+//! Epoch) und setzt `VoiceFlags::ENCRYPTED`. Der Empfangs-Loop entschluesselt
+//! vor dem Jitter-Puffer; Pakete einer Epoch ohne Schluessel, manipulierte
+//! und unverschluesselte Audio-Pakete werden verworfen. Silence-Pakete
+//! tragen keine Nutzdaten und bleiben unverschluesselt. Im Modus `dtls`
+//! schuetzt der Transport, auf dieser Ebene bleibt alles Klartext.
+//!
+//! Die Schluessel kommen ueber die TCP-Verbindung: Ist dieser Client
+//! Verteiler des Kanals, erzeugt er auf `E2EKeyRotationRequired` den neuen
+//! Schluessel und verpackt ihn fuer alle Mitglieder; sonst packt er seinen
+//! Eintrag aus der weitergeleiteten `GroupKeyDistribute` aus. Beide Wege und
+//! der Audio-Thread teilen sich einen [`VoiceSchluessel`], der den
+//! [`EpochKeyRing`] des aktuellen Kanals haelt.
+
+use std::sync::{RwLock, RwLockReadGuard, RwLockWriteGuard};
+use std::time::Instant;
+
+use speakeasy_core::types::{ChannelId, UserId};
+use speakeasy_crypto::e2e::key_ring::STANDARD_UEBERLAPPUNG;
+use speakeasy_crypto::{
+    accept_key_distribution, build_key_distribution, create_group_key, encrypt_audio,
+    generate_member_keypair, CryptoError, CryptoResult, EncryptedPayload, EpochKeyRing, GroupKey,
+    GroupKeyAlgorithm,
+};
+use speakeasy_protocol::control::{E2EKeyEnvelope, E2EKeyRotationRequiredEvent};
+use speakeasy_protocol::crypto::E2EKeyMessage;
+use speakeasy_protocol::voice::{PacketType, VoiceFlags, VoicePacket};
+
+/// Eigenes Schluesselpaar und Gruppenschluessel des aktuellen Kanals
+///
+/// Geteilt zwischen `ServerConnection` (Schluesselverteilung) und
+/// `VoiceClient` (Sende- und Empfangs-Loop).
+pub struct VoiceSchluessel {
+    /// Privater X25519-Schluessel zum Auspacken verteilter Gruppenschluessel
+    privat: [u8; 32],
+    /// Oeffentlicher Schluessel fuer `VoiceInit` (Base64)
+    oeffentlich: String,
+    /// Schluesselring des aktuellen Kanals (`None` = noch kein Schluessel)
+    ring: RwLock<Option<EpochKeyRing>>,
+}
+
+impl Default for VoiceSchluessel {
+    fn default() -> Self {
+        Self::neu()
+    }
+}
+
+impl VoiceSchluessel {
+    /// Erzeugt ein neues X25519-Schluesselpaar, noch ohne Gruppenschluessel
+    pub fn neu() -> Self {
+        let (privat, oeffentlich) = generate_member_keypair();
+        Self {
+            privat,
+            oeffentlich,
+            ring: RwLock::new(None),
+        }
+    }
+
+    fn lesen(&self) -> RwLockReadGuard<'_, Option<EpochKeyRing>> {
+        self.ring.read().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn schreiben(&self) -> RwLockWriteGuard<'_, Option<EpochKeyRing>> {
+        self.ring.write().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Oeffentlicher Schluessel fuer `VoiceInitRequest::e2e_public_key`
+    pub fn oeffentlicher_schluessel(&self) -> &str {
+        &self.oeffentlich
+    }
+
+    /// Aktuelle Epoch (`None` = noch kein Gruppenschluessel)
+    pub fn epoch(&self) -> Option<u32> {
+        self.lesen().as_ref().and_then(|r| r.current_epoch())
+    }
+
+    /// Vergisst alle Gruppenschluessel (Kanalwechsel, Trennung)
+    pub fn vergessen(&self) {
+        *self.schreiben() = None;
+    }
+
+    /// Installiert einen Gruppenschluessel
+    ///
+    /// Ein Schluessel fuer einen anderen Kanal ersetzt den ganzen Ring.
+    fn installieren(&self, key: GroupKey) -> CryptoResult<()> {
+        let mut ring = self.schreiben();
+        let anderer_kanal = ring
+            .as_ref()
+            .and_then(|r| r.current())
+            .is_some_and(|k| k.channel_id != key.channel_id);
+        if anderer_kanal {
+            *ring = None;
+        }
+        ring.get_or_insert_with(|| EpochKeyRing::new(&key.channel_id, STANDARD_UEBERLAPPUNG))
+            .install(key, Instant::now())
+    }
+
+    /// Packt den eigenen Eintrag einer weitergeleiteten `GroupKeyDistribute`
+    /// aus und installiert ihn
+    ///
+    /// Gibt die Epoch des neuen Schluessels zurueck, `None` fuer andere
+    /// Schluesselnachrichten (z.B. `KeyRevoke`).
+    pub fn verteilung_annehmen(
+        &self,
+        umschlag: &E2EKeyEnvelope,
+        user_id: &UserId,
+    ) -> CryptoResult<Option<u32>> {
+        if !matches!(umschlag.message, E2EKeyMessage::GroupKeyDistribute { .. }) {
+            return Ok(None);
+        }
+        let key = accept_key_distribution(
+            &umschlag.message,
+            user_id,
+            &self.privat,
+            &kanal(&umschlag.channel_id),
+        )?;
+        let epoch = key.epoch;
+        self.installieren(key)?;
+        Ok(Some(epoch))
+    }
+
+    /// Erzeugt als Verteiler den Schluessel fuer die angeforderte Epoch
+    ///
+    /// Installiert ihn selbst und gibt die Verteilung an alle Mitglieder
+    /// zum Senden zurueck.
+    pub fn rotation_verteilen(
+        &self,
+        ereignis: &E2EKeyRotationRequiredEvent,
+    ) -> CryptoResult<E2EKeyEnvelope> {
+        let key = create_group_key(
+            &kanal(&ereignis.channel_id),
+            u64::from(ereignis.epoch),
+            ereignis.epoch,
+            GroupKeyAlgorithm::Aes256Gcm,
+        )?;
+        let valid_from_ms = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis() as u64;
+        let message = build_key_distribution(&key, &ereignis.members, valid_from_ms)?;
+        self.installieren(key)?;
+        Ok(E2EKeyEnvelope {
+            channel_id: ereignis.channel_id,
+            message,
+        })
+    }
+
+    /// Verschluesselt die Nutzdaten eines Audio-Pakets und setzt `ENCRYPTED`
+    ///
+    /// Ohne Gruppenschluessel schlaegt das fehl; das Paket darf dann nicht
+    /// gesendet werden.
+    pub fn paket_verschluesseln(&self, paket: &mut VoicePacket) -> CryptoResult<()> {
+        let ring = self.lesen();
+        let key =
+            ring.as_ref()
+                .and_then(|r| r.current())
+                .ok_or_else(|| CryptoError::KeinSchluessel {
+                    channel_id: String::new(),
+                    epoch: 0,
+                })?;
+        let verschluesselt = encrypt_audio(
+            &paket.payload,
+            key,
+            paket.header.ssrc,
+            paket.header.sequence,
+        )?;
+        paket.payload = verschluesselt.to_bytes();
+        paket.header.flags |= VoiceFlags::ENCRYPTED;
+        Ok(())
+    }
+
+    /// Entschluesselt ein empfangenes Paket und entfernt `ENCRYPTED`
+    ///
+    /// Audio-Pakete ohne das Flag werden abgelehnt (der Server koennte sonst
+    /// Klartext unterschieben); Silence-Pakete ohne Nutzdaten bleiben
+    /// unveraendert.
+    pub fn paket_entschluesseln(
+        &self,
+        paket: &mut VoicePacket,
+        jetzt: Instant,
+    ) -> CryptoResult<()> {
+        if !paket.header.hat_flag(VoiceFlags::ENCRYPTED) {
+            if paket.header.packet_type == PacketType::Silence || paket.payload.is_empty() {
+                return Ok(());
+            }
+            return Err(CryptoError::UngueltigeDaten(
+                "Unverschluesseltes Audio im E2E-Modus".to_string(),
+            ));
+        }
+        let payload = EncryptedPayload::from_bytes(&paket.payload).ok_or_else(|| {
+            CryptoError::UngueltigeDaten("Ungueltige Payload-Struktur".to_string())
+        })?;
+        // Die AAD bindet das Paket an seinen Sender
+        if payload.aad.get(0..4) != Some(&paket.header.ssrc.to_be_bytes()[..]) {
+            return Err(CryptoError::UngueltigeDaten(
+                "SSRC passt nicht zum Paket".to_string(),
+            ));
+        }
+        let ring = self.lesen();
+        let ring = ring.as_ref().ok_or_else(|| CryptoError::KeinSchluessel {
+            channel_id: String::new(),
+            epoch: payload.nonce.epoch(),
+        })?;
+        paket.payload = ring.decrypt(&payload, jetzt)?;
+        paket.header.flags &= !VoiceFlags::ENCRYPTED;
+        Ok(())
+    }
+}
+
+/// Kanalname fuer die Gruppenschluessel
+fn kanal(channel_id: &ChannelId) -> String {
+    channel_id.inner().to_string()
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use speakeasy_protocol::control::E2EMemberKey;
+    use speakeasy_protocol::crypto::KeyRotationReason;
+    use speakeasy_protocol::voice::VoicePacketHeader;
+
+    struct Mitglied {
+        user_id: UserId,
+        schluessel: VoiceSchluessel,
+    }
+
+    fn mitglied() -> Mitglied {
+        Mitglied {
+            user_id: UserId::new(),
+            schluessel: VoiceSchluessel::neu(),
+        }
+    }
+
+    fn rotation(
+        kanal: ChannelId,
+        epoch: u32,
+        mitglieder: &[&Mitglied],
+    ) -> E2EKeyRotationRequiredEvent {
+        E2EKeyRotationRequiredEvent {
+            channel_id: kanal,
+            epoch,
+            reason: KeyRotationReason::MemberJoined,
+            members: mitglieder
+                .iter()
+                .map(|m| E2EMemberKey {
+                    user_id: m.user_id,
+                    public_key: m.schluessel.oeffentlicher_schluessel().to_string(),
+                })
+                .collect(),
+        }
+    }
+
+    /// Verteiler `a` und Empfaenger `b` mit gemeinsamem Schluessel der Epoch 0
+    fn kanal_mit_schluessel() -> (ChannelId, Mitglied, Mitglied) {
+        let (kanal, a, b) = (ChannelId::new(), mitglied(), mitglied());
+        let umschlag = a
+            .schluessel
+            .rotation_verteilen(&rotation(kanal, 0, &[&a, &b]))
+            .unwrap();
+        assert_eq!(
+            b.schluessel
+                .verteilung_annehmen(&umschlag, &b.user_id)
+                .unwrap(),
+            Some(0)
+        );
+        (kanal, a, b)
+    }
+
+    fn audio(seq: u32, ssrc: u32) -> VoicePacket {
+        VoicePacket {
+            header: VoicePacketHeader::new(PacketType::Audio, VoiceFlags::FEC, seq, 960, ssrc),
+            payload: b"opus-frame".to_vec(),
+        }
+    }
+
+    /// Wie ueber UDP: kodieren und wieder dekodieren
+    fn uebertragen(paket: &VoicePacket) -> VoicePacket {
+        VoicePacket::decode(&paket.encode()).unwrap()
+    }
+
+    #[test]
+    fn verschluesseltes_paket_kommt_beim_empfaenger_an() {
+        let (_, a, b) = kanal_mit_schluessel();
+        let mut paket = audio(5, 42);
+        a.schluessel.paket_verschluesseln(&mut paket).unwrap();
+        assert!(paket.header.hat_flag(VoiceFlags::ENCRYPTED));
+        assert_ne!(paket.payload, b"opus-frame");
+
+        let mut empfangen = uebertragen(&paket);
+        b.schluessel
+            .paket_entschluesseln(&mut empfangen, Instant::now())
+            .unwrap();
+        assert_eq!(empfangen.payload, b"opus-frame");
+        assert!(!empfangen.header.hat_flag(VoiceFlags::ENCRYPTED));
+        assert!(empfangen.header.hat_flag(VoiceFlags::FEC));
+    }
+
+    #[test]
+    fn manipuliertes_paket_wird_abgelehnt() {
+        let (_, a, b) = kanal_mit_schluessel();
+        let mut paket = audio(5, 42);
+        a.schluessel.paket_verschluesseln(&mut paket).unwrap();
+
+        let mut manipuliert = uebertragen(&paket);
+        *manipuliert.payload.last_mut().unwrap() ^= 0x01;
+        assert!(b
+            .schluessel
+            .paket_entschluesseln(&mut manipuliert, Instant::now())
+            .is_err());
+
+        // Fremde SSRC im Header (Paket eines anderen Sprechers untergeschoben)
+        let mut umgelenkt = uebertragen(&paket);
+        umgelenkt.header.ssrc = 43;
+        assert!(b
+            .schluessel
+            .paket_entschluesseln(&mut umgelenkt, Instant::now())
+            .is_err());
+    }
+
+    #[test]
+    fn ohne_schluessel_wird_nicht_gesendet_und_nicht_abgespielt() {
+        let (_, a, _) = kanal_mit_schluessel();
+        let fremd = mitglied();
+        let mut paket = audio(1, 7);
+        assert!(fremd.schluessel.paket_verschluesseln(&mut paket).is_err());
+        assert_eq!(paket.payload, b"opus-frame");
+
+        a.schluessel.paket_verschluesseln(&mut paket).unwrap();
+        assert!(matches!(
+            fremd
+                .schluessel
+                .paket_entschluesseln(&mut paket, Instant::now()),
+            Err(CryptoError::KeinSchluessel { epoch: 0, .. })
+        ));
+    }
+
+    #[test]
+    fn paket_einer_unbekannten_epoch_wird_verworfen() {
+        let (kanal, a, b) = kanal_mit_schluessel();
+        // Nur der Verteiler kennt Epoch 1 (Verteilung an b noch unterwegs)
+        a.schluessel
+            .rotation_verteilen(&rotation(kanal, 1, &[&a, &b]))
+            .unwrap();
+        assert_eq!(a.schluessel.epoch(), Some(1));
+
+        let mut paket = audio(9, 42);
+        a.schluessel.paket_verschluesseln(&mut paket).unwrap();
+        assert!(matches!(
+            b.schluessel
+                .paket_entschluesseln(&mut paket, Instant::now()),
+            Err(CryptoError::KeinSchluessel { epoch: 1, .. })
+        ));
+    }
+
+    #[test]
+    fn klartext_audio_wird_im_e2e_modus_abgelehnt() {
+        let (_, _, b) = kanal_mit_schluessel();
+        let mut klartext = audio(1, 42);
+        assert!(b
+            .schluessel
+            .paket_entschluesseln(&mut klartext, Instant::now())
+            .is_err());
+
+        let mut stille = VoicePacket::neu_silence(2, 1920, 42);
+        assert!(b
+            .schluessel
+            .paket_entschluesseln(&mut stille, Instant::now())
+            .is_ok());
+    }
+
+    #[test]
+    fn vergessen_entfernt_alle_schluessel() {
+        let (_, a, _) = kanal_mit_schluessel();
+        a.schluessel.vergessen();
+        assert_eq!(a.schluessel.epoch(), None);
+        assert!(a.schluessel.paket_verschluesseln(&mut audio(1, 1)).is_err());
+    }
+}
@@ -0,0 +1,658 @@
+//! Verbindungsstatistik – Paketverlust getrennt nach Richtung
+//!
+//! - **Downlink** (Server -> Client): lokal aus Sequenzluecken je entfernter
+//!   SSRC gemessen.
+//! - **Uplink** (Client -> Server): aus der Empfangsstatistik, die der Server
+//!   als Antwort auf den periodischen `VoiceStatsReport` zurueckschickt.
+//!
+//! Die angezeigten Raten beziehen sich jeweils auf das letzte Berichts-
+//! intervall, nicht auf die gesamte Sitzung.
+//!
+//! Pakete, die der Server absichtlich nicht weitergeleitet hat (Stumm-
+//! schaltungen, veraltete Pakete), sind kein Downlink-Verlust. Ohne
+//! Resequenzierung meldet der Server sie in der Antwort; sie werden von den
+//! erwarteten Paketen abgezogen.
+//!
+//! Zusaetzlich wird der letzte Stand der Kernel-Zaehler des UDP-Sockets
+//! gehalten: verwirft schon das eigene System Datagramme, ist das kein
+//! Netzwerkverlust.
+//!
+//! Frames, die der Wiedergabe-Mischer wegen voller Warteschlange verworfen
+//! hat (Sender liefert schneller als in Echtzeit), zaehlen ebenfalls nicht
+//! als Verlust; sie werden je Sprecher getrennt gefuehrt und dem Server
+//! gemeldet.
+//!
+//! Mit jedem Bericht wird die Sprachqualitaet der Sitzung als MOS-Note
+//! geschaetzt (siehe [`speakeasy_voice::telemetry::mos`]) und dem Server
+//! mitgeteilt.
+//!
+//! Gesendete und empfangene Voice-Pakete zaehlt [`PaketZaehler`] lock-free
+//! direkt in den Sende- und Empfangsschleifen (fuer die Diagnose).
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use speakeasy_audio::WarteschlangenStatistik;
+use speakeasy_protocol::control::{
+    SsrcOverflow, SsrcReceiveStats, VoiceStatsReport, VoiceStatsResponse,
+};
+use speakeasy_protocol::socket_statistik::SocketZaehler;
+use speakeasy_protocol::voice::{verlust_rate, SequenzStatistik};
+use speakeasy_voice::telemetry::mos::{self, MosEingabe};
+
+use crate::voice_jitter::PufferStand;
+
+/// Mindestabstand zwischen zwei Berichten an den Server
+pub const BERICHT_INTERVALL: Duration = Duration::from_secs(2);
+
+/// Downlink-Zustand einer entfernten SSRC
+#[derive(Debug, Clone, Default)]
+struct EntfernterStrom {
+    /// Laufende Statistik
+    aktuell: SequenzStatistik,
+    /// Stand beim letzten Bericht
+    letzter_bericht: SequenzStatistik,
+    /// Verlust-Rate im letzten Intervall (0.0–1.0)
+    verlust_rate: f64,
+    /// Benutzer hinter der SSRC (vom Server aufgeloest)
+    user_id: Option<String>,
+    /// Vom Server absichtlich verworfene Pakete (kumuliert)
+    unterdrueckt: u64,
+    /// Stand von `unterdrueckt` beim letzten Bericht
+    unterdrueckt_bericht: u64,
+    /// Im Wiedergabe-Mischer verworfene Frames (kumuliert)
+    ueberlauf: u64,
+}
+
+/// Downlink-Statistik eines entfernten Sprechers
+#[derive(Debug, Clone, PartialEq)]
+pub struct EntfernterVerlust {
+    pub ssrc: u32,
+    pub user_id: Option<String>,
+    pub empfangen: u64,
+    pub verloren: u64,
+    /// Verlust-Rate im letzten Intervall (0.0–1.0)
+    pub verlust_rate: f64,
+    /// Im Wiedergabe-Mischer verworfene Frames (kein Netzwerkverlust)
+    pub ueberlauf: u64,
+}
+
+/// Gesendete und empfangene Voice-Pakete, lock-free zwischen Audio-Thread,
+/// Empfangs-Task und Diagnose geteilt
+#[derive(Debug, Clone, Default)]
+pub struct PaketZaehler(Arc<PaketZaehlerInnen>);
+
+#[derive(Debug, Default)]
+struct PaketZaehlerInnen {
+    gesendet: AtomicU64,
+    empfangen: AtomicU64,
+}
+
+impl PaketZaehler {
+    /// Zaehlt ein erfolgreich gesendetes Paket
+    pub fn gesendet_erfassen(&self) {
+        self.0.gesendet.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Zaehlt ein empfangenes, gueltiges Voice-Paket
+    pub fn empfangen_erfassen(&self) {
+        self.0.empfangen.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Bisher gesendete Pakete
+    pub fn gesendet(&self) -> u64 {
+        self.0.gesendet.load(Ordering::Relaxed)
+    }
+
+    /// Bisher empfangene Pakete
+    pub fn empfangen(&self) -> u64 {
+        self.0.empfangen.load(Ordering::Relaxed)
+    }
+}
+
+/// Paketverlust-Statistik einer Voice-Sitzung
+#[derive(Debug, Default)]
+pub struct VerbindungsStatistik {
+    entfernte: HashMap<u32, EntfernterStrom>,
+    /// Downlink-Verlust aller Stroeme im letzten Intervall
+    downlink_verlust: f64,
+    /// Letzte Uplink-Statistik des Servers (received, expected)
+    uplink_letzter: Option<(u64, u64)>,
+    /// Uplink-Verlust im letzten Intervall
+    uplink_verlust: f64,
+    letzter_bericht: Option<Instant>,
+    /// Dauer des letzten Berichtsaustauschs (RTT zum Server)
+    rtt: Option<Duration>,
+    /// Letzte Kernel-Zaehler des UDP-Sockets (`None` = nicht verfuegbar)
+    socket: Option<SocketZaehler>,
+    /// Letzter Stand des Jitter-Puffers
+    puffer: PufferStand,
+    /// Stand des Jitter-Puffers beim letzten Bericht
+    puffer_bericht: PufferStand,
+    /// Aktuelle Sende-Bitrate (kbps)
+    bitrate_kbps: Option<u16>,
+    /// Geschaetzte Sprachqualitaet im letzten Intervall (`None` = noch keine RTT)
+    mos: Option<f64>,
+}
+
+impl VerbindungsStatistik {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Setzt alle Zaehler zurueck (neue Voice-Sitzung)
+    pub fn zuruecksetzen(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Verbucht ein empfangenes Voice-Paket (Downlink)
+    pub fn paket_empfangen(&mut self, ssrc: u32, sequenz: u32) {
+        self.entfernte
+            .entry(ssrc)
+            .or_default()
+            .aktuell
+            .paket(sequenz);
+    }
+
+    /// Ist ein neuer Bericht an den Server faellig?
+    pub fn bericht_faellig(&self, jetzt: Instant) -> bool {
+        self.letzter_bericht
+            .is_none_or(|t| jetzt.duration_since(t) >= BERICHT_INTERVALL)
+    }
+
+    /// Schliesst das Downlink-Intervall ab und erstellt den Bericht
+    pub fn bericht_erstellen(
+        &mut self,
+        hoechste_gesendet: Option<u32>,
+        jetzt: Instant,
+    ) -> VoiceStatsReport {
+        self.letzter_bericht = Some(jetzt);
+
+        let (mut empfangen, mut erwartet) = (0u64, 0u64);
+        for strom in self.entfernte.values_mut() {
+            let vorher = strom.letzter_bericht;
+            let unterdrueckt = strom
+                .unterdrueckt
+                .saturating_sub(strom.unterdrueckt_bericht);
+            let strom_empfangen = strom.aktuell.empfangen() - vorher.empfangen();
+            let strom_erwartet = strom
+                .aktuell
+                .erwartet()
+                .saturating_sub(vorher.erwartet())
+                .saturating_sub(unterdrueckt);
+            empfangen += strom_empfangen;
+            erwartet += strom_erwartet;
+            strom.verlust_rate = verlust_rate(strom_empfangen, strom_erwartet);
+            strom.letzter_bericht = strom.aktuell;
+            strom.unterdrueckt_bericht = strom.unterdrueckt;
+        }
+        self.downlink_verlust = verlust_rate(empfangen, erwartet);
+        self.mos = self.mos_schaetzen();
+        self.puffer_bericht = self.puffer;
+
+        VoiceStatsReport {
+            highest_sequence_sent: hoechste_gesendet,
+            downlink: self
+                .entfernte
+                .iter()
+                .map(|(ssrc, strom)| SsrcReceiveStats {
+                    ssrc: *ssrc,
+                    received: strom.aktuell.empfangen(),
+                    expected: strom.aktuell.erwartet(),
+                })
+                .collect(),
+            rtt_ms: self
+                .rtt
+                .map(|rtt| u32::try_from(rtt.as_millis()).unwrap_or(u32::MAX)),
+            mos: self.mos.map(|mos| mos as f32),
+            playback_overflow: self
+                .entfernte
+                .iter()
+                .filter(|(_, strom)| strom.ueberlauf > 0)
+                .map(|(ssrc, strom)| SsrcOverflow {
+                    ssrc: *ssrc,
+                    overflow: strom.ueberlauf,
+                })
+                .collect(),
+        }
+    }
+
+    /// Schaetzt die Sprachqualitaet des gerade abgeschlossenen Intervalls
+    ///
+    /// Beide Richtungen zaehlen: Verlust im Uplink hoeren die anderen,
+    /// Verlust im Downlink und zu spaete Pakete hoert man selbst.
+    fn mos_schaetzen(&self) -> Option<f64> {
+        let rtt_ms = u32::try_from(self.rtt?.as_millis()).unwrap_or(u32::MAX);
+        let eingereiht = self
+            .puffer
+            .eingereiht
+            .saturating_sub(self.puffer_bericht.eingereiht);
+        // Zu spaet und wegen des Latenzbudgets verworfen: beides fehlt im Ton
+        let verspaetet = (self.puffer.verspaetet + self.puffer.verworfen)
+            .saturating_sub(self.puffer_bericht.verspaetet + self.puffer_bericht.verworfen);
+        let verwurf_rate = if eingereiht == 0 {
+            0.0
+        } else {
+            verspaetet as f64 / eingereiht as f64
+        };
+        let eingabe = MosEingabe {
+            einweg_verzoegerung_ms: mos::einweg_verzoegerung_ms(rtt_ms, self.puffer.tiefe_ms),
+            verlust_rate: 1.0 - (1.0 - self.uplink_verlust) * (1.0 - self.downlink_verlust),
+            verwurf_rate,
+            bitrate_kbps: self.bitrate_kbps.map_or(mos::VOLLE_BITRATE_KBPS, f64::from),
+        };
+        Some(eingabe.mos())
+    }
+
+    /// Uebernimmt den aktuellen Stand des Jitter-Puffers
+    pub fn puffer_stand_setzen(&mut self, stand: PufferStand) {
+        // Kleinere Zaehler: neuer Puffer (Pipeline neu gestartet)
+        if stand.eingereiht < self.puffer.eingereiht {
+            self.puffer_bericht = PufferStand::default();
+        }
+        self.puffer = stand;
+    }
+
+    /// Uebernimmt die Ueberlauf-Zaehler des Wiedergabe-Mischers
+    pub fn wiedergabe_setzen(&mut self, stroeme: &[(u32, WarteschlangenStatistik)]) {
+        for (ssrc, statistik) in stroeme {
+            if let Some(strom) = self.entfernte.get_mut(ssrc) {
+                strom.ueberlauf = statistik.ueberlauf;
+            }
+        }
+    }
+
+    /// Setzt die aktuelle Sende-Bitrate fuer die Qualitaetsschaetzung
+    pub fn bitrate_setzen(&mut self, kbps: u16) {
+        self.bitrate_kbps = Some(kbps);
+    }
+
+    /// Geschaetzte Sprachqualitaet (1.0–4.5) im letzten Berichtsintervall
+    pub fn mos(&self) -> Option<f64> {
+        self.mos
+    }
+
+    /// Merkt sich die Dauer des Berichtsaustauschs fuer den naechsten Bericht
+    pub fn rtt_setzen(&mut self, rtt: Duration) {
+        self.rtt = Some(rtt);
+    }
+
+    /// Dauer des letzten Berichtsaustauschs (`None` = noch keiner)
+    pub fn rtt(&self) -> Option<Duration> {
+        self.rtt
+    }
+
+    /// Letzter gemeldeter Stand des Jitter-Puffers
+    pub fn puffer_stand(&self) -> PufferStand {
+        self.puffer
+    }
+
+    /// Uebernimmt die Antwort des Servers (Uplink-Sicht)
+    pub fn antwort_verarbeiten(&mut self, antwort: &VoiceStatsResponse) {
+        let aktuell = (antwort.uplink.received, antwort.uplink.expected);
+        let (vorher_empfangen, vorher_erwartet) = self.uplink_letzter.unwrap_or((0, 0));
+        // Zaehler kleiner als zuvor: Server hat die Sitzung neu begonnen
+        let (vorher_empfangen, vorher_erwartet) = if aktuell.1 < vorher_erwartet {
+            (0, 0)
+        } else {
+            (vorher_empfangen, vorher_erwartet)
+        };
+        self.uplink_verlust = verlust_rate(
+            aktuell.0.saturating_sub(vorher_empfangen),
+            aktuell.1 - vorher_erwartet,
+        );
+        self.uplink_letzter = Some(aktuell);
+
+        for sender in &antwort.senders {
+            if let Some(strom) = self.entfernte.get_mut(&sender.ssrc) {
+                strom.user_id = Some(sender.user_id.inner().to_string());
+            }
+        }
+        for verworfen in &antwort.suppressed {
+            if let Some(strom) = self.entfernte.get_mut(&verworfen.ssrc) {
+                strom.unterdrueckt = verworfen.suppressed;
+            }
+        }
+    }
+
+    /// Uebernimmt die zuletzt gelesenen Kernel-Zaehler des UDP-Sockets
+    pub fn socket_zaehler_setzen(&mut self, zaehler: SocketZaehler) {
+        self.socket = Some(zaehler);
+    }
+
+    /// Kernel-Zaehler des UDP-Sockets (`None` wenn nicht verfuegbar)
+    pub fn socket_zaehler(&self) -> Option<SocketZaehler> {
+        self.socket
+    }
+
+    /// Uplink-Verlust in Prozent
+    pub fn uplink_verlust_prozent(&self) -> f32 {
+        (self.uplink_verlust * 100.0) as f32
+    }
+
+    /// Downlink-Verlust in Prozent
+    pub fn downlink_verlust_prozent(&self) -> f32 {
+        (self.downlink_verlust * 100.0) as f32
+    }
+
+    /// Im Downlink verlorene Pakete aller entfernten Sprecher (Sitzung)
+    pub fn downlink_verloren(&self) -> u64 {
+        self.entfernte().iter().map(|e| e.verloren).sum()
+    }
+
+    /// Downlink-Statistik je entferntem Sprecher
+    pub fn entfernte(&self) -> Vec<EntfernterVerlust> {
+        let mut liste: Vec<EntfernterVerlust> = self
+            .entfernte
+            .iter()
+            .map(|(ssrc, strom)| EntfernterVerlust {
+                ssrc: *ssrc,
+                user_id: strom.user_id.clone(),
+                empfangen: strom.aktuell.empfangen(),
+                verloren: strom.aktuell.verloren().saturating_sub(strom.unterdrueckt),
+                verlust_rate: strom.verlust_rate,
+                ueberlauf: strom.ueberlauf,
+            })
+            .collect();
+        liste.sort_by_key(|e| e.ssrc);
+        liste
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use speakeasy_core::types::UserId;
+    use speakeasy_protocol::control::{SsrcSender, SsrcSuppressed};
+
+    const EIGENE_SSRC: u32 = 1;
+    const ENTFERNTE_SSRC: u32 = 2;
+
+    /// Synthetische Strecke: laesst jedes `n`-te Paket fallen (0 = verlustfrei)
+    fn strecke(n: u32) -> impl Fn(u32) -> bool {
+        move |seq| n == 0 || seq % n != 1
+    }
+
+    /// Simuliert `pakete` Pakete in beide Richtungen und tauscht einen Bericht aus
+    fn simulieren(
+        stat: &mut VerbindungsStatistik,
+        server_uplink: &mut SequenzStatistik,
+        uplink: impl Fn(u32) -> bool,
+        downlink: impl Fn(u32) -> bool,
+        pakete: std::ops::Range<u32>,
+        jetzt: Instant,
+    ) {
+        for seq in pakete.clone() {
+            if uplink(seq) {
+                server_uplink.paket(seq);
+            }
+            if downlink(seq) {
+                stat.paket_empfangen(ENTFERNTE_SSRC, seq);
+            }
+        }
+        let bericht = stat.bericht_erstellen(Some(pakete.end - 1), jetzt);
+        assert_eq!(bericht.downlink.len(), 1);
+        stat.antwort_verarbeiten(&VoiceStatsResponse {
+            uplink: SsrcReceiveStats {
+                ssrc: EIGENE_SSRC,
+                received: server_uplink.empfangen(),
+                expected: server_uplink.erwartet(),
+            },
+            senders: vec![],
+            suppressed: vec![],
+        });
+    }
+
+    #[test]
+    fn mos_folgt_verlust_und_verspaetung() {
+        let mut stat = VerbindungsStatistik::new();
+        let mut server = SequenzStatistik::default();
+        let jetzt = Instant::now();
+
+        // Ohne RTT gibt es noch keine Schaetzung
+        simulieren(
+            &mut stat,
+            &mut server,
+            strecke(0),
+            strecke(0),
+            0..100,
+            jetzt,
+        );
+        assert_eq!(stat.mos(), None);
+
+        stat.rtt_setzen(Duration::from_millis(40));
+        stat.bitrate_setzen(64);
+        stat.puffer_stand_setzen(PufferStand {
+            eingereiht: 100,
+            verspaetet: 0,
+            verworfen: 0,
+            tiefe_ms: 40,
+        });
+        let bericht = stat.bericht_erstellen(Some(99), jetzt + BERICHT_INTERVALL);
+        let gut = stat.mos().unwrap();
+        assert!(gut > 4.3, "{gut}");
+        assert_eq!(bericht.mos, Some(gut as f32));
+
+        // 5 % der Pakete im naechsten Intervall kommen zu spaet
+        stat.puffer_stand_setzen(PufferStand {
+            eingereiht: 200,
+            verspaetet: 5,
+            verworfen: 0,
+            tiefe_ms: 40,
+        });
+        stat.bericht_erstellen(Some(199), jetzt + BERICHT_INTERVALL * 2);
+        let mos = stat.mos().unwrap();
+        assert!(mos < gut - 0.5, "{mos}");
+    }
+
+    #[test]
+    fn uplink_verlust_bewegt_nur_uplink_zaehler() {
+        let mut stat = VerbindungsStatistik::new();
+        let mut server = SequenzStatistik::default();
+
+        simulieren(
+            &mut stat,
+            &mut server,
+            strecke(10),
+            strecke(0),
+            0..100,
+            Instant::now(),
+        );
+
+        assert!((stat.uplink_verlust_prozent() - 10.0).abs() < 0.01);
+        assert_eq!(stat.downlink_verlust_prozent(), 0.0);
+    }
+
+    #[test]
+    fn downlink_verlust_bewegt_nur_downlink_zaehler() {
+        let mut stat = VerbindungsStatistik::new();
+        let mut server = SequenzStatistik::default();
+
+        simulieren(
+            &mut stat,
+            &mut server,
+            strecke(0),
+            strecke(5),
+            0..100,
+            Instant::now(),
+        );
+
+        assert_eq!(stat.uplink_verlust_prozent(), 0.0);
+        assert!((stat.downlink_verlust_prozent() - 20.0).abs() < 0.01);
+        let entfernte = stat.entfernte();
+        assert_eq!(entfernte.len(), 1);
+        assert_eq!(entfernte[0].ssrc, ENTFERNTE_SSRC);
+        assert!(entfernte[0].verloren > 0);
+    }
+
+    #[test]
+    fn raten_beziehen_sich_auf_letztes_intervall() {
+        let mut stat = VerbindungsStatistik::new();
+        let mut server = SequenzStatistik::default();
+        let start = Instant::now();
+
+        simulieren(
+            &mut stat,
+            &mut server,
+            strecke(4),
+            strecke(4),
+            0..100,
+            start,
+        );
+        assert!(stat.uplink_verlust_prozent() > 0.0);
+
+        // Zweites Intervall ohne Verlust
+        simulieren(
+            &mut stat,
+            &mut server,
+            strecke(0),
+            strecke(0),
+            100..200,
+            start + BERICHT_INTERVALL,
+        );
+        assert_eq!(stat.uplink_verlust_prozent(), 0.0);
+        assert_eq!(stat.downlink_verlust_prozent(), 0.0);
+    }
+
+    #[test]
+    fn serverseitig_verworfene_pakete_sind_kein_verlust() {
+        let mut stat = VerbindungsStatistik::new();
+        let start = Instant::now();
+        for seq in 0..10 {
+            stat.paket_empfangen(ENTFERNTE_SSRC, seq);
+        }
+        stat.bericht_erstellen(None, start);
+        stat.antwort_verarbeiten(&VoiceStatsResponse {
+            uplink: SsrcReceiveStats {
+                ssrc: EIGENE_SSRC,
+                received: 0,
+                expected: 0,
+            },
+            senders: vec![],
+            suppressed: vec![SsrcSuppressed {
+                ssrc: ENTFERNTE_SSRC,
+                suppressed: 20,
+            }],
+        });
+
+        // 20 Pakete stumm geschaltet, eins unterwegs verloren
+        for seq in (30..60).filter(|seq| *seq != 45) {
+            stat.paket_empfangen(ENTFERNTE_SSRC, seq);
+        }
+        stat.bericht_erstellen(None, start + BERICHT_INTERVALL);
+        assert!((stat.downlink_verlust_prozent() - 100.0 / 30.0).abs() < 0.01);
+        assert_eq!(stat.entfernte()[0].verloren, 1);
+    }
+
+    #[test]
+    fn bericht_enthaelt_letzte_rtt() {
+        let mut stat = VerbindungsStatistik::new();
+        let jetzt = Instant::now();
+        assert_eq!(stat.bericht_erstellen(None, jetzt).rtt_ms, None);
+
+        stat.rtt_setzen(Duration::from_millis(48));
+        assert_eq!(stat.bericht_erstellen(None, jetzt).rtt_ms, Some(48));
+        stat.zuruecksetzen();
+        assert_eq!(stat.bericht_erstellen(None, jetzt).rtt_ms, None);
+    }
+
+    #[test]
+    fn bericht_intervall() {
+        let mut stat = VerbindungsStatistik::new();
+        let start = Instant::now();
+        assert!(stat.bericht_faellig(start));
+        stat.bericht_erstellen(None, start);
+        assert!(!stat.bericht_faellig(start + Duration::from_millis(500)));
+        assert!(stat.bericht_faellig(start + BERICHT_INTERVALL));
+    }
+
+    #[test]
+    fn sender_werden_benutzern_zugeordnet() {
+        let mut stat = VerbindungsStatistik::new();
+        stat.paket_empfangen(ENTFERNTE_SSRC, 0);
+        let uid = UserId::new();
+        stat.antwort_verarbeiten(&VoiceStatsResponse {
+            uplink: SsrcReceiveStats {
+                ssrc: EIGENE_SSRC,
+                received: 0,
+                expected: 0,
+            },
+            senders: vec![SsrcSender {
+                ssrc: ENTFERNTE_SSRC,
+                user_id: uid,
+            }],
+            suppressed: vec![],
+        });
+        assert_eq!(stat.entfernte()[0].user_id, Some(uid.inner().to_string()));
+    }
+
+    #[test]
+    fn mischer_ueberlauf_ist_kein_verlust_und_wird_gemeldet() {
+        let mut stat = VerbindungsStatistik::new();
+        let start = Instant::now();
+        for seq in 0..100 {
+            stat.paket_empfangen(ENTFERNTE_SSRC, seq);
+        }
+        stat.wiedergabe_setzen(&[(
+            ENTFERNTE_SSRC,
+            WarteschlangenStatistik {
+                ueberlauf: 40,
+                ..Default::default()
+            },
+        )]);
+
+        let bericht = stat.bericht_erstellen(None, start);
+        assert_eq!(stat.downlink_verlust_prozent(), 0.0);
+        assert_eq!(bericht.playback_overflow.len(), 1);
+        assert_eq!(bericht.playback_overflow[0].ssrc, ENTFERNTE_SSRC);
+        assert_eq!(bericht.playback_overflow[0].overflow, 40);
+        let entfernte = stat.entfernte();
+        assert_eq!(entfernte[0].verloren, 0);
+        assert_eq!(entfernte[0].ueberlauf, 40);
+    }
+
+    #[test]
+    fn socket_zaehler_bis_zum_zuruecksetzen() {
+        let mut stat = VerbindungsStatistik::new();
+        assert_eq!(stat.socket_zaehler(), None);
+
+        let zaehler = SocketZaehler {
+            verworfen: 3,
+            ..Default::default()
+        };
+        stat.socket_zaehler_setzen(zaehler);
+        assert_eq!(stat.socket_zaehler(), Some(zaehler));
+
+        stat.zuruecksetzen();
+        assert_eq!(stat.socket_zaehler(), None);
+    }
+
+    #[test]
+    fn paket_zaehler_werden_geteilt() {
+        let zaehler = PaketZaehler::default();
+        let sende_loop = zaehler.clone();
+        sende_loop.gesendet_erfassen();
+        sende_loop.gesendet_erfassen();
+        zaehler.empfangen_erfassen();
+        assert_eq!(zaehler.gesendet(), 2);
+        assert_eq!(zaehler.empfangen(), 1);
+    }
+
+    #[test]
+    fn downlink_verloren_ueber_alle_sprecher() {
+        let mut stat = VerbindungsStatistik::new();
+        for seq in [0, 1, 4] {
+            stat.paket_empfangen(ENTFERNTE_SSRC, seq);
+        }
+        for seq in [0, 2] {
+            stat.paket_empfangen(ENTFERNTE_SSRC + 1, seq);
+        }
+        assert_eq!(stat.downlink_verloren(), 3);
+    }
+}
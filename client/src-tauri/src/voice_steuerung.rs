@@ -0,0 +1,375 @@
+//! Steuerzustand der Voice-Pipeline
+//!
+//! Statt einzelner Flags (laeuft, gemutet, deaf, ...) verteilt [`Steuerung`]
+//! einen einzigen [`SteuerZustand`] per `tokio::sync::watch`. Der Audio-Thread
+//! liest pro Frame, der Empfangs-Task pro Paket genau einen Schnappschuss;
+//! Kombinationen wie "deaf, aber noch nicht gemutet" sind damit nie sichtbar.
+//!
+//! ## Uebergaenge
+//! ```text
+//! Gestoppt --starten()--> Laeuft  (neue Lauf-Nummer)
+//! Laeuft   --stoppen()--> Gestoppt
+//! ```
+//! Mute, Deaf, Nur-Zuhoeren und Notfall-Sperre sind in beiden Phasen setzbar
+//! und gelten ueber einen Neustart hinweg. Deaf sperrt das Senden, ohne den
+//! eigenen Mute-Zustand zu ueberschreiben: nach dem Undeafen gilt wieder der
+//! vorherige. Jeder Lauf hat eine eigene Nummer, damit sich Threads eines
+//! alten Laufs auch dann beenden, wenn die Pipeline inzwischen neu laeuft.
+
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+use tokio::sync::watch;
+use tracing::warn;
+
+/// So lange wartet `stop()` je Thread bzw. Task auf das Ende
+pub const STOP_FRIST: Duration = Duration::from_secs(2);
+
+/// Abfrageintervall beim Warten auf das Ende eines Threads
+const JOIN_INTERVALL: Duration = Duration::from_millis(1);
+
+/// Lebensphase der Pipeline
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Phase {
+    #[default]
+    Gestoppt,
+    Laeuft,
+}
+
+/// Zusammenhaengender Schnappschuss aller Steuer-Flags
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct SteuerZustand {
+    pub phase: Phase,
+    /// Nummer des aktuellen bzw. letzten Laufs (0 = nie gestartet)
+    pub lauf: u64,
+    /// Eigenes Mikrofon-Mute
+    pub muted: bool,
+    /// Ton deaktiviert (sperrt auch das Senden)
+    pub deafened: bool,
+    /// Nur-Zuhoeren: das Mikrofon bleibt geschlossen
+    pub nur_hoeren: bool,
+    /// Kanal notfall-stumm geschaltet (unabhaengig vom eigenen Mute)
+    pub notfall_gesperrt: bool,
+}
+
+impl SteuerZustand {
+    /// Laeuft die Pipeline?
+    pub fn laeuft(&self) -> bool {
+        self.phase == Phase::Laeuft
+    }
+
+    /// Laeuft die Pipeline noch im Lauf `lauf`?
+    pub fn gilt_fuer(&self, lauf: u64) -> bool {
+        self.laeuft() && self.lauf == lauf
+    }
+
+    /// Darf gerade nichts gesendet werden?
+    pub fn senden_gesperrt(&self) -> bool {
+        self.muted || self.deafened || self.notfall_gesperrt
+    }
+}
+
+/// Sendeseite des Steuerzustands; Klone teilen denselben Zustand
+#[derive(Clone)]
+pub struct Steuerung {
+    tx: Arc<watch::Sender<SteuerZustand>>,
+}
+
+impl Default for Steuerung {
+    fn default() -> Self {
+        Self::neu()
+    }
+}
+
+impl Steuerung {
+    pub fn neu() -> Self {
+        let (tx, _) = watch::channel(SteuerZustand::default());
+        Self { tx: Arc::new(tx) }
+    }
+
+    /// Aktueller Schnappschuss
+    pub fn zustand(&self) -> SteuerZustand {
+        *self.tx.borrow()
+    }
+
+    /// Empfaenger fuer Audio-Thread bzw. Empfangs-Task
+    pub fn abonnieren(&self) -> watch::Receiver<SteuerZustand> {
+        self.tx.subscribe()
+    }
+
+    /// Gestoppt -> Laeuft; gibt die neue Lauf-Nummer zurueck
+    ///
+    /// `None`, wenn die Pipeline bereits laeuft.
+    pub fn starten(&self) -> Option<u64> {
+        let mut lauf = None;
+        self.tx.send_if_modified(|z| {
+            if z.laeuft() {
+                return false;
+            }
+            z.phase = Phase::Laeuft;
+            z.lauf += 1;
+            lauf = Some(z.lauf);
+            true
+        });
+        lauf
+    }
+
+    /// Laeuft -> Gestoppt; `false`, wenn die Pipeline nicht lief
+    pub fn stoppen(&self) -> bool {
+        self.tx.send_if_modified(|z| {
+            let lief = z.laeuft();
+            z.phase = Phase::Gestoppt;
+            lief
+        })
+    }
+
+    /// Setzt das eigene Mute; `true` bei einer Aenderung
+    pub fn set_muted(&self, muted: bool) -> bool {
+        self.setzen(|z| &mut z.muted, muted)
+    }
+
+    /// Setzt Deaf; `true` bei einer Aenderung
+    pub fn set_deafened(&self, deafened: bool) -> bool {
+        self.setzen(|z| &mut z.deafened, deafened)
+    }
+
+    /// Setzt Nur-Zuhoeren; `true` bei einer Aenderung
+    pub fn set_nur_hoeren(&self, nur_hoeren: bool) -> bool {
+        self.setzen(|z| &mut z.nur_hoeren, nur_hoeren)
+    }
+
+    /// Setzt die Notfall-Sperre; `true` bei einer Aenderung
+    pub fn set_notfall(&self, gesperrt: bool) -> bool {
+        self.setzen(|z| &mut z.notfall_gesperrt, gesperrt)
+    }
+
+    /// Aendert ein Flag; Empfaenger werden nur bei einem Wechsel geweckt
+    fn setzen(&self, feld: fn(&mut SteuerZustand) -> &mut bool, wert: bool) -> bool {
+        self.tx
+            .send_if_modified(|z| std::mem::replace(feld(z), wert) != wert)
+    }
+}
+
+/// Wartet hoechstens `frist` auf das Ende eines Threads
+///
+/// Gibt `false` zurueck, wenn der Thread nicht rechtzeitig endet; er wird
+/// dann abgekoppelt.
+pub async fn thread_joinen(handle: JoinHandle<()>, frist: Duration) -> bool {
+    let ende = Instant::now() + frist;
+    while !handle.is_finished() {
+        if Instant::now() >= ende {
+            return abgekoppelt(&handle);
+        }
+        tokio::time::sleep(JOIN_INTERVALL).await;
+    }
+    beenden(handle)
+}
+
+/// Wie [`thread_joinen`], aber blockierend (fuer `Drop`)
+pub fn thread_joinen_blockierend(handle: JoinHandle<()>, frist: Duration) -> bool {
+    let ende = Instant::now() + frist;
+    while !handle.is_finished() {
+        if Instant::now() >= ende {
+            return abgekoppelt(&handle);
+        }
+        std::thread::sleep(JOIN_INTERVALL);
+    }
+    beenden(handle)
+}
+
+fn abgekoppelt(handle: &JoinHandle<()>) -> bool {
+    warn!(
+        thread = handle.thread().name().unwrap_or("?"),
+        "Thread hat sich nicht rechtzeitig beendet und wird abgekoppelt"
+    );
+    false
+}
+
+fn beenden(handle: JoinHandle<()>) -> bool {
+    if handle.join().is_err() {
+        warn!("Voice-Thread ist abgestuerzt");
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+    #[test]
+    fn starten_und_stoppen() {
+        let steuerung = Steuerung::neu();
+        assert!(!steuerung.zustand().laeuft());
+        assert!(!steuerung.stoppen());
+
+        assert_eq!(steuerung.starten(), Some(1));
+        assert_eq!(steuerung.starten(), None);
+        assert!(steuerung.zustand().gilt_fuer(1));
+
+        assert!(steuerung.stoppen());
+        assert!(!steuerung.stoppen());
+        assert_eq!(steuerung.starten(), Some(2));
+        assert!(!steuerung.zustand().gilt_fuer(1));
+    }
+
+    #[test]
+    fn deaf_sperrt_senden_ohne_mute_zu_ueberschreiben() {
+        let steuerung = Steuerung::neu();
+        assert!(steuerung.set_deafened(true));
+        let zustand = steuerung.zustand();
+        assert!(zustand.senden_gesperrt());
+        assert!(!zustand.muted);
+
+        assert!(steuerung.set_deafened(false));
+        assert!(!steuerung.zustand().senden_gesperrt());
+
+        steuerung.set_muted(true);
+        steuerung.set_deafened(true);
+        steuerung.set_deafened(false);
+        assert!(steuerung.zustand().senden_gesperrt());
+    }
+
+    #[test]
+    fn flags_ueberleben_neustart() {
+        let steuerung = Steuerung::neu();
+        steuerung.set_nur_hoeren(true);
+        steuerung.set_notfall(true);
+        steuerung.starten();
+        steuerung.stoppen();
+        steuerung.starten();
+        let zustand = steuerung.zustand();
+        assert!(zustand.nur_hoeren);
+        assert!(zustand.notfall_gesperrt);
+    }
+
+    #[test]
+    fn gleicher_wert_weckt_niemanden() {
+        let steuerung = Steuerung::neu();
+        let mut rx = steuerung.abonnieren();
+        assert!(!steuerung.set_muted(false));
+        assert!(!rx.has_changed().unwrap());
+        assert!(steuerung.set_muted(true));
+        assert!(rx.has_changed().unwrap());
+        assert!(rx.borrow_and_update().muted);
+    }
+
+    /// Haelt ein simuliertes Audio-Geraet offen, solange es lebt
+    struct Geraet(Arc<AtomicUsize>);
+
+    impl Geraet {
+        fn oeffnen(offen: &Arc<AtomicUsize>) -> Self {
+            offen.fetch_add(1, Ordering::SeqCst);
+            Self(Arc::clone(offen))
+        }
+    }
+
+    impl Drop for Geraet {
+        fn drop(&mut self) {
+            self.0.fetch_sub(1, Ordering::SeqCst);
+        }
+    }
+
+    /// Arbeitet wie der Audio-Thread: Geraet oeffnen, dann je "Frame" einen
+    /// Schnappschuss lesen
+    fn arbeiter(steuerung: &Steuerung, lauf: u64, offen: &Arc<AtomicUsize>) -> JoinHandle<()> {
+        let mut rx = steuerung.abonnieren();
+        let offen = Arc::clone(offen);
+        std::thread::spawn(move || {
+            let _geraet = Geraet::oeffnen(&offen);
+            loop {
+                let zustand = *rx.borrow_and_update();
+                if !zustand.gilt_fuer(lauf) {
+                    break;
+                }
+                // Ein Mute-Wechsel darf nie einen halben Zustand zeigen
+                assert!(!zustand.deafened || zustand.senden_gesperrt());
+                std::thread::sleep(Duration::from_micros(200));
+            }
+        })
+    }
+
+    #[test]
+    fn start_stop_hinterlaesst_keine_threads() {
+        let steuerung = Steuerung::neu();
+        let offen = Arc::new(AtomicUsize::new(0));
+        let fertig = Arc::new(AtomicBool::new(false));
+
+        // Parallel Mute und Deaf umschalten
+        let umschalter = {
+            let steuerung = steuerung.clone();
+            let fertig = Arc::clone(&fertig);
+            std::thread::spawn(move || {
+                let mut an = false;
+                while !fertig.load(Ordering::Relaxed) {
+                    an = !an;
+                    steuerung.set_muted(an);
+                    steuerung.set_deafened(!an);
+                }
+            })
+        };
+
+        for _ in 0..200 {
+            let lauf = steuerung.starten().unwrap();
+            let handle = arbeiter(&steuerung, lauf, &offen);
+            assert!(steuerung.stoppen());
+            assert!(thread_joinen_blockierend(handle, STOP_FRIST));
+            assert_eq!(offen.load(Ordering::SeqCst), 0);
+        }
+
+        fertig.store(true, Ordering::Relaxed);
+        umschalter.join().unwrap();
+        assert!(!steuerung.zustand().laeuft());
+    }
+
+    #[test]
+    fn alter_lauf_endet_trotz_neustart() {
+        let steuerung = Steuerung::neu();
+        let offen = Arc::new(AtomicUsize::new(0));
+
+        let alt = steuerung.starten().unwrap();
+        let alter_thread = arbeiter(&steuerung, alt, &offen);
+        steuerung.stoppen();
+        // Neustart, bevor der alte Thread den Stopp gesehen haben muss
+        let neu = steuerung.starten().unwrap();
+        let neuer_thread = arbeiter(&steuerung, neu, &offen);
+
+        assert!(thread_joinen_blockierend(alter_thread, STOP_FRIST));
+        assert!(!neuer_thread.is_finished());
+
+        steuerung.stoppen();
+        assert!(thread_joinen_blockierend(neuer_thread, STOP_FRIST));
+        assert_eq!(offen.load(Ordering::SeqCst), 0);
+    }
+
+    #[tokio::test]
+    async fn empfaenger_sieht_stopp_sofort() {
+        let steuerung = Steuerung::neu();
+        for _ in 0..100 {
+            let lauf = steuerung.starten().unwrap();
+            let mut rx = steuerung.abonnieren();
+            let task = tokio::spawn(async move {
+                while rx.borrow_and_update().gilt_fuer(lauf) {
+                    if rx.changed().await.is_err() {
+                        break;
+                    }
+                }
+            });
+            steuerung.stoppen();
+            tokio::time::timeout(STOP_FRIST, task)
+                .await
+                .expect("Empfangs-Task endet nach dem Stopp")
+                .unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn haengender_thread_wird_nach_frist_abgekoppelt() {
+        let (tx, rx) = std::sync::mpsc::channel::<()>();
+        let handle = std::thread::spawn(move || {
+            let _ = rx.recv();
+        });
+        assert!(!thread_joinen(handle, Duration::from_millis(20)).await);
+        drop(tx);
+    }
+}
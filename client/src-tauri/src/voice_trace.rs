@@ -0,0 +1,632 @@
+//! Voice-Trace – Netzwerk-Debugmodus fuer den Voice-Transport
+//!
+//! Zeichnet auf Wunsch jedes gesendete und empfangene Voice-Paket als
+//! NDJSON-Zeile auf (Zeitpunkt, Richtung, Groesse, Header-Felder). Audio-
+//! Nutzdaten werden nur mit `include_payload` mitgeschrieben.
+//!
+//! ```text
+//! Sende-/Empfangs-Loop
+//!     -> aufzeichnen(): ein Atomic-Load wenn deaktiviert
+//!     -> begrenzter Kanal (try_send, volle Queue = verworfen++)
+//!     -> Writer-Thread: NDJSON, Rotation nach ROTATION_BYTES,
+//!        Stopp beim Erreichen der Obergrenze
+//! ```
+//!
+//! Segmente heissen `<pfad>`, `<pfad>.1`, `<pfad>.2`, ... und werden von
+//! [`zusammenfassen`] in dieser Reihenfolge gelesen.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::mpsc::{self, Receiver, SyncSender};
+use std::sync::{Arc, Mutex};
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use speakeasy_protocol::voice::VoicePacketHeader;
+use tracing::{info, warn};
+
+/// Kapazitaet der Queue zwischen Audio-/Netzwerk-Threads und Writer
+const QUEUE_KAPAZITAET: usize = 4096;
+/// Groesse eines Trace-Segments bevor rotiert wird
+const ROTATION_BYTES: u64 = 16 * 1024 * 1024;
+/// Groesster Sequenzsprung, der noch als Verlust gilt (sonst Neustart des Stroms)
+const MAX_VERLUST_LAUF: u32 = 1000;
+/// Obergrenzen der Zwischenankunfts-Buckets in Millisekunden (letzter = offen)
+const ZWISCHENANKUNFT_BUCKETS_MS: [u64; 9] = [5, 10, 20, 30, 40, 60, 100, 200, 500];
+
+/// Richtung eines aufgezeichneten Pakets
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Richtung {
+    Gesendet,
+    Empfangen,
+}
+
+/// Ein Paket in der Queue zum Writer (Zeitpunkt bereits beim Erfassen)
+struct Erfassung {
+    zeitpunkt: Instant,
+    richtung: Richtung,
+    groesse: usize,
+    header: VoicePacketHeader,
+    payload: Option<Vec<u8>>,
+}
+
+/// Eine Zeile der Trace-Datei
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "typ", rename_all = "snake_case")]
+pub enum TraceZeile {
+    /// Erste Zeile jedes Segments
+    Start {
+        start_unix_ms: u64,
+        segment: u32,
+        include_payload: bool,
+    },
+    Paket(TracePaket),
+}
+
+/// Aufgezeichnetes Voice-Paket
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TracePaket {
+    /// Mikrosekunden seit Start der Aufzeichnung (monoton)
+    pub t_us: u64,
+    pub richtung: Richtung,
+    /// Groesse des UDP-Datagramms in Bytes
+    pub groesse: usize,
+    pub packet_type: u8,
+    pub flags: u16,
+    pub sequence: u32,
+    pub timestamp: u32,
+    pub ssrc: u32,
+    /// Opus-Nutzdaten als Hex (nur mit `include_payload`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub payload: Option<String>,
+}
+
+/// Abschlussbericht einer Aufzeichnung
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TraceBericht {
+    pub pfad: String,
+    pub segmente: u32,
+    pub bytes: u64,
+    pub pakete: u64,
+    /// Pakete, die wegen voller Queue nicht aufgezeichnet wurden
+    pub verworfen: u64,
+    /// Aufzeichnung wurde wegen Erreichen der Obergrenze beendet
+    pub obergrenze_erreicht: bool,
+    /// Schreibfehler, der die Aufzeichnung beendet hat
+    pub fehler: Option<String>,
+}
+
+/// Laufende Aufzeichnung
+struct Aufzeichnung {
+    sender: SyncSender<Erfassung>,
+    writer: std::thread::JoinHandle<TraceBericht>,
+}
+
+/// Schalter und Queue des Voice-Traces (von Sende- und Empfangs-Loop geteilt)
+#[derive(Default)]
+pub struct VoiceTrace {
+    aktiv: Arc<AtomicBool>,
+    include_payload: AtomicBool,
+    verworfen: AtomicU64,
+    aufzeichnung: Mutex<Option<Aufzeichnung>>,
+}
+
+impl VoiceTrace {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Gibt zurueck ob gerade aufgezeichnet wird
+    pub fn ist_aktiv(&self) -> bool {
+        self.aktiv.load(Ordering::Relaxed)
+    }
+
+    /// Startet eine Aufzeichnung nach `pfad` mit hoechstens `max_bytes`
+    pub fn starten(
+        &self,
+        pfad: PathBuf,
+        max_bytes: u64,
+        include_payload: bool,
+    ) -> Result<(), String> {
+        let mut aufzeichnung = self.aufzeichnung.lock().map_err(|e| e.to_string())?;
+        if self.ist_aktiv() {
+            return Err("Voice-Trace laeuft bereits".to_string());
+        }
+        // Ein nach Erreichen der Obergrenze beendeter Writer wird hier abgeraeumt
+        if let Some(alt) = aufzeichnung.take() {
+            drop(alt.sender);
+            let _ = alt.writer.join();
+        }
+
+        let datei = File::create(&pfad)
+            .map_err(|e| format!("Trace-Datei konnte nicht angelegt werden: {}", e))?;
+        let (sender, empfaenger) = mpsc::sync_channel(QUEUE_KAPAZITAET);
+        let writer = TraceWriter {
+            pfad: pfad.clone(),
+            datei: BufWriter::new(datei),
+            start: Instant::now(),
+            max_bytes,
+            include_payload,
+            segment: 0,
+            segment_bytes: 0,
+            bericht: TraceBericht {
+                pfad: pfad.to_string_lossy().into_owned(),
+                segmente: 1,
+                ..Default::default()
+            },
+        };
+        let aktiv = Arc::clone(&self.aktiv);
+        let writer = std::thread::Builder::new()
+            .name("voice-trace".to_string())
+            .spawn(move || writer.ausfuehren(empfaenger, aktiv))
+            .map_err(|e| format!("Trace-Writer konnte nicht gestartet werden: {}", e))?;
+
+        self.verworfen.store(0, Ordering::Relaxed);
+        self.include_payload
+            .store(include_payload, Ordering::Relaxed);
+        self.aktiv.store(true, Ordering::Relaxed);
+        *aufzeichnung = Some(Aufzeichnung { sender, writer });
+
+        info!(pfad = %pfad.display(), max_bytes, include_payload, "Voice-Trace gestartet");
+        Ok(())
+    }
+
+    /// Beendet die Aufzeichnung und wartet bis der Writer alles geschrieben hat
+    ///
+    /// Blockiert kurz (Flush); aus async-Code per `spawn_blocking` aufrufen.
+    pub fn stoppen(&self) -> Option<TraceBericht> {
+        self.aktiv.store(false, Ordering::Relaxed);
+        let aufzeichnung = self.aufzeichnung.lock().ok()?.take()?;
+        // Sender droppen beendet die Empfangsschleife des Writers
+        drop(aufzeichnung.sender);
+        let mut bericht = aufzeichnung.writer.join().ok()?;
+        bericht.verworfen = self.verworfen.load(Ordering::Relaxed);
+        info!(
+            pakete = bericht.pakete,
+            verworfen = bericht.verworfen,
+            bytes = bericht.bytes,
+            "Voice-Trace beendet"
+        );
+        Some(bericht)
+    }
+
+    /// Vermerkt ein Voice-Paket (nicht blockierend)
+    ///
+    /// Ist der Trace deaktiviert, kostet der Aufruf nur einen Atomic-Load.
+    /// Bei voller Queue oder gleichzeitigem Start/Stopp wird das Paket
+    /// verworfen und gezaehlt.
+    pub fn aufzeichnen(
+        &self,
+        richtung: Richtung,
+        header: &VoicePacketHeader,
+        groesse: usize,
+        payload: &[u8],
+    ) {
+        if !self.aktiv.load(Ordering::Relaxed) {
+            return;
+        }
+        let erfassung = Erfassung {
+            zeitpunkt: Instant::now(),
+            richtung,
+            groesse,
+            header: *header,
+            payload: self
+                .include_payload
+                .load(Ordering::Relaxed)
+                .then(|| payload.to_vec()),
+        };
+        let gesendet = match self.aufzeichnung.try_lock() {
+            Ok(aufzeichnung) => aufzeichnung
+                .as_ref()
+                .is_some_and(|a| a.sender.try_send(erfassung).is_ok()),
+            Err(_) => false,
+        };
+        if !gesendet {
+            self.verworfen.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+}
+
+/// Schreibt Erfassungen als NDJSON (laeuft im eigenen Thread)
+struct TraceWriter {
+    pfad: PathBuf,
+    datei: BufWriter<File>,
+    start: Instant,
+    max_bytes: u64,
+    include_payload: bool,
+    segment: u32,
+    segment_bytes: u64,
+    bericht: TraceBericht,
+}
+
+impl TraceWriter {
+    fn ausfuehren(
+        mut self,
+        empfaenger: Receiver<Erfassung>,
+        aktiv: Arc<AtomicBool>,
+    ) -> TraceBericht {
+        let ergebnis = self.segment_beginnen().and_then(|()| {
+            while let Ok(erfassung) = empfaenger.recv() {
+                if !self.schreiben(erfassung)? {
+                    self.bericht.obergrenze_erreicht = true;
+                    info!(
+                        max_bytes = self.max_bytes,
+                        "Voice-Trace: Obergrenze erreicht"
+                    );
+                    break;
+                }
+            }
+            self.datei.flush()
+        });
+        aktiv.store(false, Ordering::Relaxed);
+        if let Err(e) = ergebnis {
+            warn!("Voice-Trace abgebrochen: {}", e);
+            self.bericht.fehler = Some(e.to_string());
+        }
+        self.bericht
+    }
+
+    /// Schreibt eine Erfassung; `false` wenn die Obergrenze erreicht ist
+    fn schreiben(&mut self, erfassung: Erfassung) -> std::io::Result<bool> {
+        let t_us = erfassung
+            .zeitpunkt
+            .saturating_duration_since(self.start)
+            .as_micros() as u64;
+        let zeile = TraceZeile::Paket(TracePaket {
+            t_us,
+            richtung: erfassung.richtung,
+            groesse: erfassung.groesse,
+            packet_type: erfassung.header.packet_type as u8,
+            flags: erfassung.header.flags,
+            sequence: erfassung.header.sequence,
+            timestamp: erfassung.header.timestamp,
+            ssrc: erfassung.header.ssrc,
+            payload: erfassung.payload.as_deref().map(hex),
+        });
+        let zeile = zeile_kodieren(&zeile);
+        let laenge = zeile.len() as u64;
+
+        if self.bericht.bytes + laenge > self.max_bytes {
+            return Ok(false);
+        }
+        if self.segment_bytes + laenge > ROTATION_BYTES {
+            self.rotieren()?;
+        }
+        self.datei.write_all(&zeile)?;
+        self.segment_bytes += laenge;
+        self.bericht.bytes += laenge;
+        self.bericht.pakete += 1;
+        Ok(true)
+    }
+
+    fn rotieren(&mut self) -> std::io::Result<()> {
+        self.datei.flush()?;
+        self.segment += 1;
+        self.datei = BufWriter::new(File::create(segment_pfad(&self.pfad, self.segment))?);
+        self.segment_bytes = 0;
+        self.bericht.segmente += 1;
+        self.segment_beginnen()
+    }
+
+    fn segment_beginnen(&mut self) -> std::io::Result<()> {
+        let start_unix_ms = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or_default();
+        let zeile = zeile_kodieren(&TraceZeile::Start {
+            start_unix_ms,
+            segment: self.segment,
+            include_payload: self.include_payload,
+        });
+        self.datei.write_all(&zeile)?;
+        self.segment_bytes += zeile.len() as u64;
+        self.bericht.bytes += zeile.len() as u64;
+        Ok(())
+    }
+}
+
+fn zeile_kodieren(zeile: &TraceZeile) -> Vec<u8> {
+    let mut bytes = serde_json::to_vec(zeile).unwrap_or_default();
+    bytes.push(b'\n');
+    bytes
+}
+
+/// Pfad des n-ten Segments (0 = Basisdatei)
+fn segment_pfad(basis: &Path, segment: u32) -> PathBuf {
+    if segment == 0 {
+        return basis.to_path_buf();
+    }
+    let mut name = basis.as_os_str().to_os_string();
+    name.push(format!(".{}", segment));
+    PathBuf::from(name)
+}
+
+fn hex(bytes: &[u8]) -> String {
+    use std::fmt::Write as _;
+    bytes
+        .iter()
+        .fold(String::with_capacity(bytes.len() * 2), |mut s, b| {
+            let _ = write!(s, "{:02x}", b);
+            s
+        })
+}
+
+// ---------------------------------------------------------------------------
+// Offline-Auswertung
+// ---------------------------------------------------------------------------
+
+/// Auswertung eines einzelnen Stroms (Richtung + SSRC)
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StromZusammenfassung {
+    pub richtung: Option<Richtung>,
+    pub ssrc: u32,
+    pub pakete: u64,
+    /// Fehlende Sequenznummern abzueglich spaeter eingetroffener Pakete
+    pub verloren: u64,
+    /// Laenge eines Verlustlaufs -> Anzahl
+    pub verlust_laeufe: BTreeMap<u32, u64>,
+    /// Pakete, die nach einer hoeheren Sequenznummer eintrafen
+    pub umsortiert: u64,
+    /// Umsortier-Abstand (hoechste Sequenz - Sequenz) -> Anzahl
+    pub umsortier_abstaende: BTreeMap<u32, u64>,
+    /// Zwischenankunftszeiten: (Obergrenze in ms, `None` = offen, Anzahl)
+    pub zwischenankunft_ms: Vec<(Option<u64>, u64)>,
+}
+
+/// Auswertung einer Trace-Datei
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TraceZusammenfassung {
+    pub segmente: u32,
+    pub ungueltige_zeilen: u64,
+    pub stroeme: Vec<StromZusammenfassung>,
+}
+
+#[derive(Default)]
+struct StromAuswertung {
+    zusammenfassung: StromZusammenfassung,
+    hoechste: Option<u32>,
+    letzte_ankunft_us: Option<u64>,
+    luecken: u64,
+}
+
+impl StromAuswertung {
+    fn paket(&mut self, paket: &TracePaket) {
+        let z = &mut self.zusammenfassung;
+        z.pakete += 1;
+
+        if let Some(letzte) = self.letzte_ankunft_us {
+            let ms = paket.t_us.saturating_sub(letzte) / 1000;
+            let bucket = ZWISCHENANKUNFT_BUCKETS_MS
+                .iter()
+                .position(|&grenze| ms < grenze)
+                .unwrap_or(ZWISCHENANKUNFT_BUCKETS_MS.len());
+            z.zwischenankunft_ms[bucket].1 += 1;
+        }
+        self.letzte_ankunft_us = Some(paket.t_us);
+
+        match self.hoechste {
+            None => self.hoechste = Some(paket.sequence),
+            Some(hoechste) if paket.sequence > hoechste => {
+                let lauf = paket.sequence - hoechste - 1;
+                if (1..=MAX_VERLUST_LAUF).contains(&lauf) {
+                    *z.verlust_laeufe.entry(lauf).or_default() += 1;
+                    self.luecken += lauf as u64;
+                }
+                self.hoechste = Some(paket.sequence);
+            }
+            Some(hoechste) => {
+                z.umsortiert += 1;
+                *z.umsortier_abstaende
+                    .entry(hoechste - paket.sequence)
+                    .or_default() += 1;
+            }
+        }
+        z.verloren = self.luecken.saturating_sub(z.umsortiert);
+    }
+}
+
+/// Wertet eine Trace-Datei samt Folgesegmenten aus
+pub fn zusammenfassen(pfad: &Path) -> std::io::Result<TraceZusammenfassung> {
+    let mut zusammenfassung = TraceZusammenfassung::default();
+    let mut stroeme: HashMap<(Richtung, u32), StromAuswertung> = HashMap::new();
+
+    loop {
+        let datei = match File::open(segment_pfad(pfad, zusammenfassung.segmente)) {
+            Ok(datei) => datei,
+            Err(e) if zusammenfassung.segmente > 0 && e.kind() == std::io::ErrorKind::NotFound => {
+                break
+            }
+            Err(e) => return Err(e),
+        };
+        zusammenfassung.segmente += 1;
+
+        for zeile in BufReader::new(datei).lines() {
+            let zeile = zeile?;
+            if zeile.trim().is_empty() {
+                continue;
+            }
+            match serde_json::from_str::<TraceZeile>(&zeile) {
+                Ok(TraceZeile::Paket(paket)) => stroeme
+                    .entry((paket.richtung, paket.ssrc))
+                    .or_insert_with(|| StromAuswertung {
+                        zusammenfassung: StromZusammenfassung {
+                            richtung: Some(paket.richtung),
+                            ssrc: paket.ssrc,
+                            zwischenankunft_ms: ZWISCHENANKUNFT_BUCKETS_MS
+                                .iter()
+                                .map(|&grenze| (Some(grenze), 0))
+                                .chain(std::iter::once((None, 0)))
+                                .collect(),
+                            ..Default::default()
+                        },
+                        ..Default::default()
+                    })
+                    .paket(&paket),
+                Ok(TraceZeile::Start { .. }) => {}
+                Err(_) => zusammenfassung.ungueltige_zeilen += 1,
+            }
+        }
+    }
+
+    zusammenfassung.stroeme = stroeme.into_values().map(|s| s.zusammenfassung).collect();
+    zusammenfassung
+        .stroeme
+        .sort_by_key(|s| (s.richtung, s.ssrc));
+    Ok(zusammenfassung)
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use speakeasy_protocol::voice::PacketType;
+
+    fn header(sequence: u32) -> VoicePacketHeader {
+        VoicePacketHeader::new(PacketType::Audio, 0, sequence, sequence * 960, 7)
+    }
+
+    fn temp_pfad(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("speakeasy-trace-{}-{}", std::process::id(), name))
+    }
+
+    fn paket(t_ms: u64, sequence: u32) -> TracePaket {
+        TracePaket {
+            t_us: t_ms * 1000,
+            richtung: Richtung::Empfangen,
+            groesse: 80,
+            packet_type: 0,
+            flags: 0,
+            sequence,
+            timestamp: 0,
+            ssrc: 7,
+            payload: None,
+        }
+    }
+
+    #[test]
+    fn deaktiviert_zeichnet_nichts_auf() {
+        let trace = VoiceTrace::new();
+        trace.aufzeichnen(Richtung::Gesendet, &header(1), 80, &[1, 2, 3]);
+        assert!(!trace.ist_aktiv());
+        assert_eq!(trace.verworfen.load(Ordering::Relaxed), 0);
+        assert!(trace.stoppen().is_none());
+    }
+
+    #[test]
+    fn aufzeichnung_ohne_payload() {
+        let pfad = temp_pfad("ohne-payload.ndjson");
+        let trace = VoiceTrace::new();
+        trace.starten(pfad.clone(), 1024 * 1024, false).unwrap();
+        for seq in 0..10 {
+            trace.aufzeichnen(Richtung::Gesendet, &header(seq), 80, &[0xAB; 64]);
+        }
+        let bericht = trace.stoppen().unwrap();
+        assert_eq!(bericht.pakete + bericht.verworfen, 10);
+        assert!(!bericht.obergrenze_erreicht);
+
+        let inhalt = std::fs::read_to_string(&pfad).unwrap();
+        assert!(!inhalt.contains("abab"));
+        let zusammenfassung = zusammenfassen(&pfad).unwrap();
+        assert_eq!(zusammenfassung.stroeme[0].pakete, bericht.pakete);
+        std::fs::remove_file(&pfad).unwrap();
+    }
+
+    #[test]
+    fn payload_nur_auf_wunsch() {
+        let pfad = temp_pfad("mit-payload.ndjson");
+        let trace = VoiceTrace::new();
+        trace.starten(pfad.clone(), 1024 * 1024, true).unwrap();
+        trace.aufzeichnen(Richtung::Empfangen, &header(1), 19, &[0xAB, 0x01, 0xFF]);
+        trace.stoppen().unwrap();
+
+        let inhalt = std::fs::read_to_string(&pfad).unwrap();
+        assert!(inhalt.contains("\"payload\":\"ab01ff\""));
+        std::fs::remove_file(&pfad).unwrap();
+    }
+
+    #[test]
+    fn stoppt_an_der_obergrenze() {
+        let pfad = temp_pfad("obergrenze.ndjson");
+        let trace = VoiceTrace::new();
+        trace.starten(pfad.clone(), 2048, false).unwrap();
+        for seq in 0..200 {
+            trace.aufzeichnen(Richtung::Gesendet, &header(seq), 80, &[]);
+            std::thread::yield_now();
+        }
+        // Writer beendet sich selbst und schaltet den Trace ab
+        for _ in 0..100 {
+            if !trace.ist_aktiv() {
+                break;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        }
+        assert!(!trace.ist_aktiv());
+        let bericht = trace.stoppen().unwrap();
+        assert!(bericht.obergrenze_erreicht);
+        assert!(bericht.bytes <= 2048);
+        assert!(std::fs::metadata(&pfad).unwrap().len() <= 2048);
+        std::fs::remove_file(&pfad).unwrap();
+    }
+
+    #[test]
+    fn segmentpfade() {
+        let basis = Path::new("/tmp/trace.ndjson");
+        assert_eq!(segment_pfad(basis, 0), basis);
+        assert_eq!(segment_pfad(basis, 2), Path::new("/tmp/trace.ndjson.2"));
+    }
+
+    #[test]
+    fn auswertung_verlust_umsortierung_zwischenankunft() {
+        let mut strom = StromAuswertung {
+            zusammenfassung: StromZusammenfassung {
+                zwischenankunft_ms: ZWISCHENANKUNFT_BUCKETS_MS
+                    .iter()
+                    .map(|&g| (Some(g), 0))
+                    .chain(std::iter::once((None, 0)))
+                    .collect(),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        // 0, 1, 4 (Luecke 2), 3 (umsortiert), 5, 9 (Luecke 3)
+        for (t, seq) in [(0, 0), (20, 1), (40, 4), (45, 3), (65, 5), (665, 9)] {
+            strom.paket(&paket(t, seq));
+        }
+        let z = strom.zusammenfassung;
+        assert_eq!(z.pakete, 6);
+        assert_eq!(z.verlust_laeufe, BTreeMap::from([(2, 1), (3, 1)]));
+        assert_eq!(z.umsortiert, 1);
+        assert_eq!(z.umsortier_abstaende, BTreeMap::from([(1, 1)]));
+        // 5 Luecken, davon eine spaeter eingetroffen
+        assert_eq!(z.verloren, 4);
+        // 20 ms -> Bucket <30, 5 ms -> Bucket <10, 600 ms -> offen
+        assert_eq!(z.zwischenankunft_ms[3], (Some(30), 3));
+        assert_eq!(z.zwischenankunft_ms[1], (Some(10), 1));
+        assert_eq!(z.zwischenankunft_ms[9], (None, 1));
+    }
+
+    #[test]
+    fn ungueltige_zeilen_werden_gezaehlt() {
+        let pfad = temp_pfad("ungueltig.ndjson");
+        let mut inhalt = String::from("kein json\n");
+        inhalt.push_str(&serde_json::to_string(&TraceZeile::Paket(paket(0, 1))).unwrap());
+        inhalt.push('\n');
+        std::fs::write(&pfad, inhalt).unwrap();
+
+        let z = zusammenfassen(&pfad).unwrap();
+        assert_eq!(z.segmente, 1);
+        assert_eq!(z.ungueltige_zeilen, 1);
+        assert_eq!(z.stroeme.len(), 1);
+        std::fs::remove_file(&pfad).unwrap();
+    }
+}
@@ -1,4 +1,5 @@
-import { invoke } from "@tauri-apps/api/core";
+import { Channel, invoke } from "@tauri-apps/api/core";
+import { listen, type UnlistenFn } from "@tauri-apps/api/event";
 
 // --- Typen ---
 
@@ -28,6 +29,8 @@ export interface CodecConfig {
   application: "voip" | "audio" | "low_delay";
   fec: boolean;
   dtx: boolean;
+  /** Abstand der Silence-Pakete in Sprechpausen bei aktivem DTX (ms) */
+  dtxKeepaliveMs?: number;
   channels: "mono" | "stereo";
 }
 
@@ -52,6 +55,8 @@ export interface DspConfig {
   echoCancellation: {
     enabled: boolean;
     tailLength: number;
+    /** Fester Versatz zwischen Ausgabe und Echo im Mikrofon (ms) */
+    delayOffsetMs?: number;
   };
   deesser: {
     enabled: boolean;
@@ -65,8 +70,13 @@ export interface JitterConfig {
   minBuffer: number;
   maxBuffer: number;
   adaptive: boolean;
+  /** Hoechste hingenommene Puffer-Verzoegerung in ms, unabhaengig vom Jitter */
+  latencyBudget: number;
 }
 
+/** Voreinstellung des Jitter-Puffers */
+export type JitterMode = "low_latency" | "stability";
+
 export interface AudioSettingsConfig {
   inputDeviceId: string | null;
   outputDeviceId: string | null;
@@ -97,9 +107,89 @@ export interface AudioStats {
   noiseFloor: number;
   isClipping: boolean;
   latency: LatencyBreakdown;
-  packetLoss: number;
+  /** Paketverlust Client -> Server in Prozent */
+  uplinkLoss: number;
+  /** Paketverlust Server -> Client in Prozent */
+  downlinkLoss: number;
+  /** RTT zum Server in ms (0 = noch keine Messung) */
   rtt: number;
+  /** Aktuelle Sende-Bitrate in kbps (0 = kein Voice) */
   bitrate: number;
+  /** Unterlaeufe des Playback-Puffers seit Start des Voice-Clients */
+  playbackUnderruns: number;
+  /** Geschaetzte Sprachqualitaet (MOS 1-4.5), null ohne Voice-Messung */
+  mos: number | null;
+  /** Codec-Fehler seit Start des Voice-Clients */
+  codecErrors: CodecErrorStats;
+}
+
+/** Codec-Fehler je Kategorie */
+export interface CodecErrorStats {
+  invalidFrameSize: number;
+  invalidPacket: number;
+  bufferTooSmall: number;
+  internalOpusError: number;
+  /** Zuletzt von libopus gemeldeter Fehlercode */
+  lastOpusCode: number | null;
+  /** Zu kurze Frames, mit Stille aufgefuellt */
+  framesPadded: number;
+  /** Decoder-Neustarts nach Fehlern in Folge */
+  decoderResets: number;
+}
+
+export interface RemoteStreamStats {
+  ssrc: number;
+  userId: string | null;
+  received: number;
+  lost: number;
+  loss: number;
+  /** Wegen voller Mischer-Warteschlange verworfene Frames (kein Verlust) */
+  overflow: number;
+}
+
+export type QosStatus =
+  | { status: "deaktiviert" }
+  | { status: "aktiv"; dscp: number }
+  | { status: "abgelehnt"; dscp: number; grund: string };
+
+/** Kernel-Zaehler eines UDP-Sockets */
+export interface SocketZaehler {
+  empfangs_warteschlange: number;
+  sende_warteschlange: number;
+  /** Vom System verworfene Datagramme seit Sitzungsbeginn */
+  verworfen: number;
+}
+
+export interface VoiceDiagnostics {
+  uplinkLoss: number;
+  downlinkLoss: number;
+  remote: RemoteStreamStats[];
+  /** DSCP-Markierung des Voice-UDP-Sockets */
+  voiceQos: QosStatus;
+  /** DSCP-Markierung der TCP-Control-Verbindung */
+  controlQos: QosStatus;
+  /** Kernel-Zaehler des Voice-UDP-Sockets (null = nicht verfuegbar) */
+  voiceSocket: SocketZaehler | null;
+  /** Codec-Fehler seit Start des Voice-Clients */
+  codecErrors: CodecErrorStats;
+}
+
+/** Diagnose von Control-Verbindung und Voice-Transport */
+export interface ConnectionDiagnostics {
+  /** Laufzeit des letzten Pings der Control-Verbindung (null = keine Antwort) */
+  tcpRttMs: number | null;
+  /** Median der UDP-Laufzeit zum Voice-Port (null = nicht messbar) */
+  udpRttMs: number | null;
+  packetsSent: number;
+  packetsReceived: number;
+  /** Im Downlink verlorene Pakete der laufenden Sitzung */
+  packetsLost: number;
+  jitterBufferMs: number;
+  bitrateKbps: number | null;
+  localUdpAddr: string | null;
+  remoteUdpAddr: string | null;
+  /** Krypto-Modus des Servers (none, dtls, e2e) */
+  cryptoMode: string | null;
 }
 
 export interface CalibrationResult {
@@ -129,6 +219,106 @@ export async function getAudioStats(): Promise<AudioStats> {
   return invoke("get_audio_stats");
 }
 
+export async function getVoiceDiagnostics(): Promise<VoiceDiagnostics> {
+  return invoke("get_voice_diagnostics");
+}
+
+export async function getConnectionDiagnostics(): Promise<ConnectionDiagnostics> {
+  return invoke("get_connection_diagnostics");
+}
+
+/** Ereignis der Voice-Pipeline */
+export type VoiceEvent =
+  | {
+      typ: "stream_uebersprungen";
+      ssrc: number;
+      codec: string;
+      grund: string;
+    }
+  | {
+      /** Mikrofon per Headset-Taste bzw. im System stumm (oder nicht mehr) */
+      typ: "hardware_mute_detected";
+      muted: boolean;
+    }
+  | {
+      /** Es werden laenger ueberwiegend Hintergrundgeraeusche gesendet */
+      typ: "background_noise_warning";
+      /** Anteil der Hintergrundgeraeusche (0.0 - 1.0) */
+      score: number;
+    }
+  | {
+      /** Der Jitter verlangt mehr Puffer, als das Latenzbudget erlaubt */
+      typ: "network_unstable";
+      budget_ms: number;
+    };
+
+/** Holt alle seit dem letzten Aufruf aufgetretenen Voice-Ereignisse ab */
+export async function takeVoiceEvents(): Promise<VoiceEvent[]> {
+  return invoke("take_voice_events");
+}
+
+/**
+ * Verstaerkt die Rauschunterdrueckung nach einer Hintergrundgeraeusch-Warnung
+ * um eine Stufe; gibt die neue Stufe zurueck
+ */
+export async function applyNoiseSuggestion(): Promise<string> {
+  return invoke("apply_noise_suggestion");
+}
+
+/**
+ * Stellt den Jitter-Puffer auf einen Modus um (wirkt ab dem naechsten
+ * Kanalbeitritt); gibt die neue Konfiguration zurueck
+ */
+export async function applyJitterMode(mode: JitterMode): Promise<JitterConfig> {
+  return invoke("apply_jitter_mode", { mode });
+}
+
+export interface VoiceTraceReport {
+  pfad: string;
+  segmente: number;
+  bytes: number;
+  pakete: number;
+  /** Pakete, die wegen voller Queue nicht aufgezeichnet wurden */
+  verworfen: number;
+  obergrenzeErreicht: boolean;
+  fehler: string | null;
+}
+
+export interface VoiceTraceStream {
+  richtung: "gesendet" | "empfangen" | null;
+  ssrc: number;
+  pakete: number;
+  verloren: number;
+  /** Laenge eines Verlustlaufs -> Anzahl */
+  verlustLaeufe: Record<string, number>;
+  umsortiert: number;
+  umsortierAbstaende: Record<string, number>;
+  /** [Obergrenze in ms (null = offen), Anzahl] */
+  zwischenankunftMs: [number | null, number][];
+}
+
+export interface VoiceTraceSummary {
+  segmente: number;
+  ungueltigeZeilen: number;
+  stroeme: VoiceTraceStream[];
+}
+
+export async function startVoiceTrace(
+  path: string,
+  maxMb: number,
+  includePayload = false,
+): Promise<void> {
+  return invoke("start_voice_trace", { path, maxMb, includePayload });
+}
+
+export async function stopVoiceTrace(): Promise<VoiceTraceReport | null> {
+  return invoke("stop_voice_trace");
+}
+
+export async function summarizeVoiceTrace(path: string): Promise<VoiceTraceSummary> {
+  return invoke("summarize_voice_trace", { path });
+}
+
 export async function playTestSound(): Promise<void> {
   return invoke("play_test_sound");
 }
@@ -141,6 +331,202 @@ export async function stopAudioMonitor(): Promise<void> {
   return invoke("stop_audio_monitor");
 }
 
+// --- Event-Sounds ---
+
+export type SoundEvent = "join" | "leave" | "poke" | "mention" | "mute_toggle";
+
+export interface EventSoundSettings {
+  /** Lautstaerke der Event-Sounds in Prozent (unabhaengig von der Sprache) */
+  volume: number;
+  /** Maximale Absenkung der Sprache waehrend eines Sounds in Prozent */
+  maxDucking: number;
+  join: boolean;
+  leave: boolean;
+  poke: boolean;
+  mention: boolean;
+  muteToggle: boolean;
+  /** Verzeichnis mit eigenen WAV/OGG-Dateien (null = eingebaute Sounds) */
+  soundPackDir: string | null;
+}
+
+export async function getEventSoundSettings(): Promise<EventSoundSettings> {
+  return invoke("get_event_sound_settings");
+}
+
+export async function setEventSoundSettings(
+  config: EventSoundSettings
+): Promise<void> {
+  return invoke("set_event_sound_settings", { config });
+}
+
+export async function playEventSound(event: SoundEvent): Promise<void> {
+  return invoke("play_event_sound", { event });
+}
+
+// --- QoS / DSCP ---
+
+export interface QosSettings {
+  /** DSCP fuer Voice-Pakete (46 = EF, null = keine Markierung) */
+  voiceDscp: number | null;
+  /** DSCP fuer die Control-Verbindung (26 = AF31, null = keine Markierung) */
+  controlDscp: number | null;
+}
+
+export async function getQosSettings(): Promise<QosSettings> {
+  return invoke("get_qos_settings");
+}
+
+export async function setQosSettings(config: QosSettings): Promise<void> {
+  return invoke("set_qos_settings", { config });
+}
+
+// --- Push-to-Talk ---
+
+export interface PttState {
+  mode: AudioSettingsConfig["voiceMode"];
+  key: string | null;
+  /** Darf gerade gesendet werden (Sprach-Erkennung: immer) */
+  transmitting: boolean;
+  speaking: boolean;
+}
+
+/** Setzt Sprach-Modus und PTT-Taste (Tastenname wie im Einstellungsdialog) */
+export async function setPttConfig(
+  mode: AudioSettingsConfig["voiceMode"],
+  key: string | null
+): Promise<PttState> {
+  return invoke("set_ptt_config", { mode, key });
+}
+
+export async function getPttState(): Promise<PttState> {
+  return invoke("get_ptt_state");
+}
+
+/** Meldet jeden Wechsel des eigenen Sprech-Zustands */
+export async function onSpeakingChanged(
+  handler: (speaking: boolean) => void
+): Promise<UnlistenFn> {
+  return listen<{ speaking: boolean }>("speaking_changed", (e) =>
+    handler(e.payload.speaking)
+  );
+}
+
+// --- Hardware-Stummschaltung ---
+
+export interface HardwareMuteSettings {
+  enabled: boolean;
+  /** Dauer eines exakten Null-Signals bis zur Meldung (500 - 60000 ms) */
+  zeroSignalMs: number;
+  /** Eigene Stummschaltung dem erkannten Zustand folgen lassen */
+  autoSync: boolean;
+}
+
+export async function getHardwareMuteSettings(): Promise<HardwareMuteSettings> {
+  return invoke("get_hardware_mute_settings");
+}
+
+export async function setHardwareMuteSettings(
+  config: HardwareMuteSettings
+): Promise<void> {
+  return invoke("set_hardware_mute_settings", { config });
+}
+
+// --- Lautstaerke pro Benutzer ---
+
+/** Gespeicherte Einstellung fuer einen Benutzer */
+export interface UserAudio {
+  /** Verstaerkung (0.0 - 2.0, 1.0 = unveraendert) */
+  gain: number;
+  muted: boolean;
+  /** Zuletzt gehoert bzw. geaendert (Unix-Sekunden) */
+  lastSeen: number;
+}
+
+/** Lautstaerke und Stummschaltung pro Benutzer-ID */
+export interface UserAudioPreferences {
+  users: Record<string, UserAudio>;
+  /** Eintraege ohne Kontakt werden nach so vielen Tagen entfernt (Standard: 90) */
+  retentionDays: number;
+}
+
+export async function getUserAudioPreferences(): Promise<UserAudioPreferences> {
+  return invoke("get_user_audio_preferences");
+}
+
+/** Uebernimmt gespeicherte Einstellungen, gibt die bereinigte Tabelle zurueck */
+export async function setUserAudioPreferences(
+  config: UserAudioPreferences
+): Promise<UserAudioPreferences> {
+  return invoke("set_user_audio_preferences", { config });
+}
+
+export async function setUserVolume(
+  userId: string,
+  gain: number
+): Promise<UserAudioPreferences> {
+  return invoke("set_user_volume", { userId, gain });
+}
+
+/** Lautstaerke des Benutzers hinter einer SSRC; gespeichert wird pro Benutzer */
+export async function setSsrcVolume(
+  ssrc: number,
+  gain: number
+): Promise<UserAudioPreferences> {
+  return invoke("set_ssrc_volume", { ssrc, gain });
+}
+
+export interface UserVolume {
+  userId: string;
+  /** SSRC im aktuellen Kanal (null = nicht im Kanal) */
+  ssrc: number | null;
+  gain: number;
+  muted: boolean;
+}
+
+export async function getUserVolumes(): Promise<UserVolume[]> {
+  return invoke("get_user_volumes");
+}
+
+export async function setUserMuted(
+  userId: string,
+  muted: boolean
+): Promise<UserAudioPreferences> {
+  return invoke("set_user_muted", { userId, muted });
+}
+
+/** Vergisst die Einstellung eines Benutzers (ohne userId: aller Benutzer) */
+export async function resetUserAudioPreferences(
+  userId?: string
+): Promise<UserAudioPreferences> {
+  return invoke("reset_user_audio_preferences", { userId: userId ?? null });
+}
+
+// --- Einstellungen exportieren / importieren ---
+
+export interface SettingsExport {
+  audio?: AudioSettingsConfig | null;
+  eventSounds?: EventSoundSettings | null;
+  qos?: QosSettings | null;
+  hardwareMute?: HardwareMuteSettings | null;
+  /** Nur enthalten bzw. uebernommen mit includeUserAudio */
+  userAudio?: UserAudioPreferences | null;
+  /** Entwuerfe des Eingabefelds, ebenfalls nur mit includeUserAudio */
+  drafts?: Drafts | null;
+}
+
+export async function exportSettings(
+  includeUserAudio = false
+): Promise<SettingsExport> {
+  return invoke("export_settings", { includeUserAudio });
+}
+
+export async function importSettings(
+  settings: SettingsExport,
+  includeUserAudio = false
+): Promise<SettingsExport> {
+  return invoke("import_settings", { settings, includeUserAudio });
+}
+
 export interface ServerInfo {
   name: string;
   description: string;
@@ -149,6 +535,13 @@ export interface ServerInfo {
   online_clients: number;
   uptime_secs: number;
   channels: ChannelInfo[];
+  motd: Motd | null;
+}
+
+/** Nachricht des Tages (Markdown-Teilmenge, vom Server bereinigt) */
+export interface Motd {
+  markdown: string;
+  version: number;
 }
 
 export interface ChannelInfo {
@@ -158,6 +551,17 @@ export interface ChannelInfo {
   parent_id: string | null;
   clients: ClientInfo[];
   max_clients: number;
+  /** Unterkanaele vorhanden, die noch nicht geladen sind */
+  has_children: boolean;
+  child_count: number;
+  /** Notfall-Stummschaltung aktiv: nur Ausgenommene werden gehoert */
+  emergency_muted: boolean;
+  /** Mindestabstand zwischen zwei Chat-Nachrichten in Sekunden (0 = aus) */
+  slow_mode_secs: number;
+  /** So lange nach dem Senden sind eigene Nachrichten aenderbar (Sekunden, 0 = unbegrenzt) */
+  edit_window_secs: number;
+  /** Beitritt nur nach Freigabe: vorher knockChannel() */
+  join_by_approval: boolean;
 }
 
 export interface ClientInfo {
@@ -166,6 +570,16 @@ export interface ClientInfo {
   is_muted: boolean;
   is_deafened: boolean;
   is_self: boolean;
+  /** Nur-Zuhoerer: sendet nie Audio */
+  is_listener: boolean;
+  /** Spricht gerade (laut Server) */
+  is_speaking: boolean;
+  /** Letzte Sprechaktivitaet (Unix-Millisekunden) */
+  last_spoke_at: number | null;
+  /** Durch die Notfall-Stummschaltung stumm (Mikrofon ausgegraut) */
+  is_emergency_muted: boolean;
+  /** Serverseitige Soundboard-Wiedergabe statt eines Benutzers */
+  is_soundboard: boolean;
 }
 
 export interface ConnectOptions {
@@ -178,17 +592,81 @@ export interface ConnectOptions {
 export interface ConnectResult {
   success: boolean;
   must_change_password: boolean;
+  welcome_message: string | null;
+  motd: Motd | null;
+  /** Hoechstlaenge einer Chat-Nachricht auf diesem Server (Zeichen) */
+  max_message_chars: number;
+}
+
+/** Fehler von connect_to_server (siehe ConnectError im Backend) */
+type ConnectError =
+  | {
+      kind: "incompatible";
+      client_version: string;
+      server_version: string | null;
+      suggestion: string;
+    }
+  | { kind: "failed"; message: string };
+
+/** Client und Server sprechen unvertraegliche Protokollversionen */
+export class IncompatibleServerError extends Error {
+  constructor(
+    readonly clientVersion: string,
+    readonly serverVersion: string | null,
+    readonly suggestion: string
+  ) {
+    super(suggestion);
+    this.name = "IncompatibleServerError";
+  }
+
+  toString(): string {
+    const server = this.serverVersion ?? "unbekannt";
+    return `Server nicht kompatibel (Client ${this.clientVersion}, Server ${server}). ${this.suggestion}`;
+  }
+}
+
+/** Vom Server nicht angebotene Funktionen nach dem Verbinden */
+export interface CapabilitiesDegraded {
+  /** Leer = alle Funktionen verfuegbar */
+  missing: string[];
 }
 
 // --- IPC Commands ---
 
+/**
+ * Verbindet mit dem Server
+ *
+ * Wirft bei unvertraeglichen Protokollversionen einen
+ * `IncompatibleServerError`, sonst die Fehlermeldung als Text.
+ */
 export async function connectToServer(opts: ConnectOptions): Promise<ConnectResult> {
-  return invoke("connect_to_server", {
-    address: opts.address,
-    port: opts.port,
-    username: opts.username,
-    password: opts.password ?? null,
-  });
+  try {
+    return await invoke<ConnectResult>("connect_to_server", {
+      address: opts.address,
+      port: opts.port,
+      username: opts.username,
+      password: opts.password ?? null,
+    });
+  } catch (e) {
+    const fehler = e as ConnectError;
+    if (fehler?.kind === "incompatible") {
+      throw new IncompatibleServerError(
+        fehler.client_version,
+        fehler.server_version,
+        fehler.suggestion
+      );
+    }
+    throw fehler?.kind === "failed" ? fehler.message : e;
+  }
+}
+
+/** Meldet nach jedem Verbinden, welche Funktionen der Server nicht anbietet */
+export async function onCapabilitiesDegraded(
+  handler: (event: CapabilitiesDegraded) => void
+): Promise<UnlistenFn> {
+  return listen<CapabilitiesDegraded>("capabilities_degraded", (e) =>
+    handler(e.payload)
+  );
 }
 
 export async function getMustChangePassword(): Promise<boolean> {
@@ -207,14 +685,142 @@ export async function disconnect(): Promise<void> {
   return invoke("disconnect");
 }
 
-export async function joinChannel(channelId: string): Promise<void> {
-  return invoke("join_channel", { channelId });
+/** Tritt einem Kanal bei; mit `listenOnly` bleibt das Mikrofon geschlossen */
+export async function joinChannel(channelId: string, listenOnly = false): Promise<void> {
+  return invoke("join_channel", { channelId, listenOnly });
+}
+
+/** Wechselt im aktuellen Kanal zwischen Senden und Nur-Zuhoeren */
+export async function setListenOnly(listenOnly: boolean): Promise<void> {
+  return invoke("set_listen_only", { listenOnly });
+}
+
+// --- Einladungen und Beitrittsanfragen ---
+
+export type InviteOutcome = "accepted" | "declined" | "expired";
+
+export interface InviteSent {
+  invite_id: string;
+  expires_in_secs: number;
+}
+
+/** Laedt einen verbundenen Benutzer in den eigenen Kanal ein */
+export async function inviteToChannel(
+  channelId: string,
+  userId: string,
+  message?: string
+): Promise<InviteSent> {
+  return invoke("invite_to_channel", { channelId, userId, message: message ?? null });
+}
+
+/** Nimmt eine Einladung an (tritt ohne Passwort bei) oder lehnt sie ab */
+export async function answerChannelInvite(
+  inviteId: string,
+  accept: boolean,
+  listenOnly = false
+): Promise<void> {
+  return invoke("answer_channel_invite", { inviteId, accept, listenOnly });
+}
+
+export interface KnockSent {
+  knock_id: string;
+  expires_in_secs: number;
+}
+
+/** Bittet um Einlass in einen Kanal mit Freigabe; nach Einlass joinChannel() */
+export async function knockChannel(channelId: string, message?: string): Promise<KnockSent> {
+  return invoke("knock_channel", { channelId, message: message ?? null });
+}
+
+export interface ChannelKnockResult {
+  knock_id: string;
+  channel_id: string;
+  requester_id: string;
+  outcome: InviteOutcome;
+  /** Wer entschieden hat (fehlt bei Ablauf) */
+  decided_by: string | null;
+}
+
+/** Laesst einen Anfragenden ein oder lehnt ihn ab */
+export async function answerChannelKnock(
+  knockId: string,
+  admit: boolean
+): Promise<ChannelKnockResult> {
+  return invoke("answer_channel_knock", { knockId, admit });
+}
+
+export interface ChannelInvite {
+  invite_id: string;
+  channel_id: string;
+  channel_name: string;
+  inviter_id: string;
+  inviter_name: string;
+  message: string | null;
+  expires_in_secs: number;
+}
+
+export async function onChannelInvite(
+  handler: (invite: ChannelInvite) => void
+): Promise<UnlistenFn> {
+  return listen<ChannelInvite>("channel_invite", (e) => handler(e.payload));
+}
+
+export interface ChannelInviteResult {
+  invite_id: string;
+  channel_id: string;
+  target_user_id: string;
+  outcome: InviteOutcome;
+}
+
+/** Ausgang einer eigenen Einladung */
+export async function onChannelInviteResult(
+  handler: (result: ChannelInviteResult) => void
+): Promise<UnlistenFn> {
+  return listen<ChannelInviteResult>("channel_invite_result", (e) => handler(e.payload));
+}
+
+export interface ChannelKnock {
+  knock_id: string;
+  channel_id: string;
+  requester_id: string;
+  requester_name: string;
+  message: string | null;
+  expires_in_secs: number;
+}
+
+/** Beitrittsanfrage an den eigenen Kanal (nur mit Freigabe-Recht) */
+export async function onChannelKnock(
+  handler: (knock: ChannelKnock) => void
+): Promise<UnlistenFn> {
+  return listen<ChannelKnock>("channel_knock", (e) => handler(e.payload));
+}
+
+/** Ausgang einer Beitrittsanfrage (eigene oder von anderen Freigebenden) */
+export async function onChannelKnockResult(
+  handler: (result: ChannelKnockResult) => void
+): Promise<UnlistenFn> {
+  return listen<ChannelKnockResult>("channel_knock_result", (e) => handler(e.payload));
 }
 
 export async function leaveChannel(): Promise<void> {
   return invoke("leave_channel");
 }
 
+/** Laedt Unterkanaele nach; danach getServerInfo() erneut abrufen */
+export async function expandChannel(channelId: string): Promise<void> {
+  return invoke("expand_channel", { channelId });
+}
+
+/** Alle Mitglieder eines sehr vollen Kanals (seitenweise nachgeladen) */
+export async function getChannelMembers(channelId: string): Promise<ClientInfo[]> {
+  return invoke("get_channel_members", { channelId });
+}
+
+/** Meldet Benutzeraktivitaet fuer die AFK-Erkennung (Backend drosselt auf 1/min) */
+export async function reportActivity(): Promise<void> {
+  return invoke("report_activity");
+}
+
 export async function getAudioDevices(): Promise<AudioDevice[]> {
   return invoke("get_audio_devices");
 }
@@ -235,6 +841,41 @@ export async function getServerInfo(): Promise<ServerInfo> {
   return invoke("get_server_info");
 }
 
+/** Ergebnis einer Latenzmessung per UDP-Ping */
+export interface LatencyMeasurement {
+  /** null = keine Antwort */
+  minMs: number | null;
+  medianMs: number | null;
+  /** Anteil unbeantworteter Proben (0.0 - 1.0) */
+  verlust: number;
+  gesendet: number;
+  empfangen: number;
+}
+
+export interface ServerTarget {
+  address: string;
+  port: number;
+}
+
+/** Misst die Latenz zu einem Server (ohne Anmeldung) */
+export async function measureServerLatency(
+  address: string,
+  port: number,
+): Promise<LatencyMeasurement> {
+  return invoke("measure_server_latency", { address, port });
+}
+
+/** Misst alle Server gleichzeitig; Schluessel ist `adresse:port` */
+export async function refreshBookmarkLatencies(
+  servers: ServerTarget[],
+): Promise<Record<string, LatencyMeasurement>> {
+  return invoke("refresh_bookmark_latencies", { servers });
+}
+
+export async function getBookmarkLatencies(): Promise<Record<string, LatencyMeasurement>> {
+  return invoke("get_bookmark_latencies");
+}
+
 // --- Chat-Typen (Phase 4) ---
 
 export interface FileInfo {
@@ -259,16 +900,94 @@ export interface ChatMessage {
 
 // --- Chat IPC Commands (Phase 4) ---
 
+/** Fehler von send_message (siehe SendMessageError im Backend) */
+type SendMessageError =
+  | { kind: "rate_limited"; retry_after_secs: number; message: string }
+  | { kind: "failed"; message: string };
+
+/** Nachricht vom Langsam-Modus abgelehnt; erneut senden nach `retryAfterSecs` */
+export class SlowModeError extends Error {
+  constructor(readonly retryAfterSecs: number, message: string) {
+    super(message);
+    this.name = "SlowModeError";
+  }
+
+  toString(): string {
+    return `Langsam-Modus: naechste Nachricht in ${this.retryAfterSecs} s`;
+  }
+}
+
+/**
+ * Sendet eine Nachricht
+ *
+ * Wirft im Langsam-Modus einen `SlowModeError` mit der Wartezeit, sonst die
+ * Fehlermeldung als Text.
+ */
 export async function sendMessage(
   channelId: string,
   content: string,
   replyTo?: string
 ): Promise<ChatMessage> {
-  return invoke("send_message", {
-    channelId,
-    content,
-    replyTo: replyTo ?? null,
-  });
+  try {
+    return await invoke<ChatMessage>("send_message", {
+      channelId,
+      content,
+      replyTo: replyTo ?? null,
+    });
+  } catch (e) {
+    const fehler = e as SendMessageError;
+    if (fehler?.kind === "rate_limited") {
+      throw new SlowModeError(fehler.retry_after_secs, fehler.message);
+    }
+    throw fehler?.kind === "failed" ? fehler.message : e;
+  }
+}
+
+/** Hoechstlaenge einer Chat-Nachricht fuer den Zeichenzaehler */
+export async function getMessageLimit(): Promise<number> {
+  return invoke("get_message_limit");
+}
+
+// --- Entwuerfe des Eingabefelds ---
+
+export interface Draft {
+  text: string;
+  /** Zuletzt bearbeitet (Unix-Sekunden) */
+  updatedAt: number;
+}
+
+/** Entwuerfe pro Kanal-ID; wie die Benutzer-Lautstaerken mit den Einstellungen speichern */
+export interface Drafts {
+  drafts: Record<string, Draft>;
+}
+
+/** Speichert einen Entwurf (leer = entfernen), gibt die Tabelle zum Speichern zurueck */
+export async function saveDraft(channelId: string, text: string): Promise<Drafts> {
+  return invoke("save_draft", { channelId, text });
+}
+
+export async function getDraft(channelId: string): Promise<string | null> {
+  return invoke("get_draft", { channelId });
+}
+
+export async function clearDraft(channelId: string): Promise<Drafts> {
+  return invoke("clear_draft", { channelId });
+}
+
+export async function getDrafts(): Promise<Drafts> {
+  return invoke("get_drafts");
+}
+
+/** Uebernimmt gespeicherte Entwuerfe, gibt die gekuerzte Tabelle zurueck */
+export async function setDrafts(config: Drafts): Promise<Drafts> {
+  return invoke("set_drafts", { config });
+}
+
+/** Nach einer bestaetigten Nachricht wurde der Entwurf ihres Kanals verworfen */
+export async function onDraftsChanged(
+  handler: (drafts: Drafts) => void
+): Promise<UnlistenFn> {
+  return listen<Drafts>("drafts_changed", (e) => handler(e.payload));
 }
 
 export async function getMessageHistory(
@@ -283,6 +1002,79 @@ export async function getMessageHistory(
   });
 }
 
+/**
+ * Durchsucht einen Kanal; Treffer neueste zuerst, `%` und `_` gelten woertlich.
+ * Weitere Treffer: `created_at` und `id` des letzten Treffers uebergeben.
+ */
+export async function searchMessages(
+  channelId: string,
+  query: string,
+  before?: string,
+  beforeId?: string,
+  limit?: number
+): Promise<ChatMessage[]> {
+  return invoke("search_messages", {
+    channelId,
+    query,
+    before: before ?? null,
+    beforeId: beforeId ?? null,
+    limit: limit ?? 50,
+  });
+}
+
+export interface ResolvedUser {
+  user_id: string;
+  username: string;
+  display_name: string;
+  /** Konto geloescht, Namen sind ein Platzhalter */
+  deleted: boolean;
+}
+
+export interface ResolvedChannel {
+  channel_id: string;
+  name: string;
+  /** Kanal geloescht, Name ist ein Platzhalter */
+  deleted: boolean;
+}
+
+export interface ResolvedNames {
+  users: ResolvedUser[];
+  channels: ResolvedChannel[];
+}
+
+/** Namen zu Benutzer- und Kanal-IDs; unbekannte fragt der Client beim Server nach */
+export async function resolveDisplayNames(
+  userIds: string[],
+  channelIds: string[] = []
+): Promise<ResolvedNames> {
+  return invoke("resolve_display_names", {
+    ids: { user_ids: userIds, channel_ids: channelIds },
+  });
+}
+
+export interface HistoryComplete {
+  total: number;
+  /** Cursor fuer aeltere Nachrichten (null = keine weiteren) */
+  next_before: string | null;
+}
+
+/** Laedt die History schrittweise; `onChunk` erhaelt Teilstuecke, aelteste zuerst */
+export async function streamMessageHistory(
+  channelId: string,
+  onChunk: (messages: ChatMessage[]) => void,
+  before?: string,
+  limit?: number
+): Promise<HistoryComplete> {
+  const channel = new Channel<ChatMessage[]>();
+  channel.onmessage = onChunk;
+  return invoke("stream_message_history", {
+    channelId,
+    before: before ?? null,
+    limit: limit ?? 50,
+    onChunk: channel,
+  });
+}
+
 export async function editMessage(
   messageId: string,
   content: string
@@ -294,6 +1086,95 @@ export async function deleteMessage(messageId: string): Promise<void> {
   return invoke("delete_message", { messageId });
 }
 
+export interface ChatMessageEdited {
+  id: string;
+  channel_id: string;
+  content: string;
+  edited_at: string;
+}
+
+export interface ChatMessageDeleted {
+  id: string;
+  channel_id: string;
+}
+
+/** Neue Nachrichten anderer Kanalmitglieder (eigene kommen per sendMessage) */
+export async function onChatMessage(
+  handler: (message: ChatMessage) => void
+): Promise<UnlistenFn> {
+  return listen<ChatMessage>("chat_message", (e) => handler(e.payload));
+}
+
+export async function onChatMessageEdited(
+  handler: (edit: ChatMessageEdited) => void
+): Promise<UnlistenFn> {
+  return listen<ChatMessageEdited>("chat_message_edited", (e) =>
+    handler(e.payload)
+  );
+}
+
+export async function onChatMessageDeleted(
+  handler: (deleted: ChatMessageDeleted) => void
+): Promise<UnlistenFn> {
+  return listen<ChatMessageDeleted>("chat_message_deleted", (e) =>
+    handler(e.payload)
+  );
+}
+
+/** Geaenderte Kanal-Einstellungen (z.B. Langsam-Modus durch Moderatoren) */
+export interface ChannelEdited {
+  channel_id: string;
+  slow_mode_secs: number;
+  edit_window_secs: number;
+}
+
+export async function onChannelEdited(
+  handler: (edit: ChannelEdited) => void
+): Promise<UnlistenFn> {
+  return listen<ChannelEdited>("channel_edited", (e) => handler(e.payload));
+}
+
+/** An-/Abmeldung, Kanalwechsel oder Umbenennung eines Benutzers */
+export interface PresenceChanged {
+  kind: "connected" | "disconnected" | "joined" | "left" | "updated";
+  user_id: string;
+  username: string;
+  /** Nur bei joined/left */
+  channel_id: string | null;
+}
+
+export async function onPresenceChanged(
+  handler: (change: PresenceChanged) => void
+): Promise<UnlistenFn> {
+  return listen<PresenceChanged>("presence_changed", (e) => handler(e.payload));
+}
+
+/** Ein Kanalmitglied beginnt oder beendet eine Sprechphase (vom Server entprellt) */
+export interface ClientSpeaking {
+  user_id: string;
+  channel_id: string;
+  speaking: boolean;
+}
+
+export async function onClientSpeaking(
+  handler: (event: ClientSpeaking) => void
+): Promise<UnlistenFn> {
+  return listen<ClientSpeaking>("client_speaking", (e) => handler(e.payload));
+}
+
+/** Die automatische Verlaengerung der Session ist gescheitert (neu anmelden) */
+export interface SessionRenewalFailed {
+  message: string;
+}
+
+export async function onSessionRenewalFailed(
+  handler: (event: SessionRenewalFailed) => void
+): Promise<UnlistenFn> {
+  return listen<SessionRenewalFailed>("session_renewal_failed", (e) =>
+    handler(e.payload)
+  );
+}
+
 export async function uploadFile(
   channelId: string,
   file: File
@@ -308,9 +1189,13 @@ export async function uploadFile(
   });
 }
 
-export async function downloadFile(fileId: string): Promise<Uint8Array> {
-  const data: number[] = await invoke("download_file", { fileId });
-  return new Uint8Array(data);
+/**
+ * Laedt eine Datei nach targetPath herunter (Datei darf noch nicht existieren).
+ * Gibt die Anzahl geschriebener Bytes zurueck; bei falscher Checksum,
+ * abgelaufenem Link oder geloeschter Datei wird mit einer Meldung abgelehnt.
+ */
+export async function downloadFile(fileId: string, targetPath: string): Promise<number> {
+  return invoke("download_file", { fileId, targetPath });
 }
 
 export async function listFiles(channelId: string): Promise<FileInfo[]> {
@@ -340,7 +1225,9 @@ export async function editChannel(
   name?: string,
   description?: string,
   password?: string,
-  maxClients?: number
+  maxClients?: number,
+  slowModeSecs?: number,
+  joinByApproval?: boolean
 ): Promise<void> {
   return invoke("edit_channel", {
     channelId,
@@ -348,6 +1235,8 @@ export async function editChannel(
     description: description ?? null,
     password: password ?? null,
     maxClients: maxClients ?? null,
+    slowModeSecs: slowModeSecs ?? null,
+    joinByApproval: joinByApproval ?? null,
   });
 }
 
@@ -488,6 +1377,14 @@ export interface AdminServerInfo {
   uptime_secs: number;
   online_clients: number;
   max_clients: number;
+  letzter_shutdown_unsauber?: boolean;
+}
+
+export interface AdminServerStart {
+  gestartet_um: string;
+  version: string;
+  sauber_beendet: boolean;
+  config_hash: string;
 }
 
 // Hilfs-Klasse fuer Admin-REST-Requests
@@ -529,6 +1426,14 @@ export async function adminGetServer(): Promise<AdminServerInfo> {
   return adminFetch("/v1/server");
 }
 
+// Startprotokoll (neueste Starts zuerst)
+export async function adminGetServerHistory(
+  limit?: number
+): Promise<AdminServerStart[]> {
+  const qs = limit !== undefined ? `?limit=${limit}` : "";
+  return adminFetch(`/v1/server/history${qs}`);
+}
+
 // Server-Einstellungen aktualisieren
 export async function adminUpdateServer(data: {
   name?: string;
@@ -1,10 +1,25 @@
-import { createSignal, onMount } from "solid-js";
+import { createSignal, onCleanup, onMount, Show } from "solid-js";
 import { useNavigate } from "@solidjs/router";
-import { toggleMute, toggleDeafen, disconnect, getCurrentUsername } from "../bridge";
+import {
+  toggleMute,
+  toggleDeafen,
+  disconnect,
+  getCurrentUsername,
+  getHardwareMuteSettings,
+  takeVoiceEvents,
+  applyNoiseSuggestion,
+  applyJitterMode,
+  onSpeakingChanged,
+} from "../bridge";
 import styles from "./Statusbar.module.css";
 
 export default function Statusbar() {
   const [muted, setMuted] = createSignal(false);
+  const [hardwareMuted, setHardwareMuted] = createSignal(false);
+  const [autoSync, setAutoSync] = createSignal(false);
+  const [speaking, setSpeaking] = createSignal(false);
+  const [noiseScore, setNoiseScore] = createSignal<number | null>(null);
+  const [unstableBudget, setUnstableBudget] = createSignal<number | null>(null);
   const [deafened, setDeafened] = createSignal(false);
   const [away, setAway] = createSignal(false);
   const [connected] = createSignal(true);
@@ -18,8 +33,39 @@ export default function Statusbar() {
     } catch {
       // kein Username verfuegbar
     }
+    try {
+      setAutoSync((await getHardwareMuteSettings()).autoSync);
+    } catch {
+      // Standard: kein Auto-Sync
+    }
+  });
+
+  // Eigener Sprech-Zustand (PTT-Taste bzw. Sprach-Erkennung)
+  const unlistenSpeaking = onSpeakingChanged(setSpeaking);
+  onCleanup(() => {
+    unlistenSpeaking.then((unlisten) => unlisten()).catch(() => {});
   });
 
+  // Hardware-Stummschaltung (Headset-Taste, System-Mute),
+  // Hintergrundgeraeusch- und Netzwerk-Warnungen abholen
+  const eventTimer = setInterval(async () => {
+    try {
+      for (const ev of await takeVoiceEvents()) {
+        if (ev.typ === "hardware_mute_detected") {
+          setHardwareMuted(ev.muted);
+          if (autoSync()) setMuted(ev.muted);
+        } else if (ev.typ === "background_noise_warning") {
+          setNoiseScore(ev.score);
+        } else if (ev.typ === "network_unstable") {
+          setUnstableBudget(ev.budget_ms);
+        }
+      }
+    } catch {
+      // Backend nicht erreichbar - Zustand unveraendert lassen
+    }
+  }, 1000);
+  onCleanup(() => clearInterval(eventTimer));
+
   async function handleToggleMute() {
     try {
       const result = await toggleMute();
@@ -38,6 +84,24 @@ export default function Statusbar() {
     }
   }
 
+  async function handleApplyNoiseSuggestion() {
+    try {
+      await applyNoiseSuggestion();
+    } catch (e) {
+      console.error("Rauschunterdrueckung konnte nicht angepasst werden:", e);
+    }
+    setNoiseScore(null);
+  }
+
+  async function handleApplyStabilityMode() {
+    try {
+      await applyJitterMode("stability");
+    } catch (e) {
+      console.error("Stabilitaetsmodus konnte nicht gesetzt werden:", e);
+    }
+    setUnstableBudget(null);
+  }
+
   function handleToggleAway() {
     setAway((v) => !v);
   }
@@ -56,19 +120,70 @@ export default function Statusbar() {
       {/* User-Info */}
       <div class={styles.userInfo}>
         <span
-          class={`${styles.statusDot} ${connected() ? styles.online : styles.offline}`}
+          class={`${styles.statusDot} ${connected() ? styles.online : styles.offline} ${speaking() ? styles.speaking : ""}`}
+          title={speaking() ? "Sendet" : undefined}
         />
         <span class={styles.username}>{username() ?? "Benutzer"}</span>
+        <Show when={noiseScore() !== null}>
+          <span
+            class={styles.noiseHint}
+            title={`Etwa ${Math.round((noiseScore() ?? 0) * 100)} % deines Signals sind Hintergrundgeraeusche`}
+          >
+            Hintergrundgeraeusche
+          </span>
+          <button
+            class={styles.controlBtn}
+            onClick={handleApplyNoiseSuggestion}
+            title="Rauschunterdrueckung eine Stufe erhoehen"
+          >
+            FILTER+
+          </button>
+          <button
+            class={styles.controlBtn}
+            onClick={() => setNoiseScore(null)}
+            title="Hinweis ausblenden"
+          >
+            X
+          </button>
+        </Show>
+        <Show when={unstableBudget() !== null}>
+          <span
+            class={styles.noiseHint}
+            title={`Das Netzwerk schwankt staerker, als ${unstableBudget()} ms Puffer ausgleichen koennen`}
+          >
+            Netzwerk instabil
+          </span>
+          <button
+            class={styles.controlBtn}
+            onClick={handleApplyStabilityMode}
+            title="Stabilitaetsmodus: mehr Puffer, dafuer mehr Verzoegerung (ab dem naechsten Kanalbeitritt)"
+          >
+            STABIL
+          </button>
+          <button
+            class={styles.controlBtn}
+            onClick={() => setUnstableBudget(null)}
+            title="Hinweis ausblenden"
+          >
+            X
+          </button>
+        </Show>
       </div>
 
       {/* Audio-Controls */}
       <div class={styles.controls}>
         <button
-          class={`${styles.controlBtn} ${muted() ? styles.active : ""}`}
+          class={`${styles.controlBtn} ${muted() || hardwareMuted() ? styles.active : ""}`}
           onClick={handleToggleMute}
-          title={muted() ? "Stummschaltung aufheben" : "Stummschalten"}
+          title={
+            hardwareMuted()
+              ? "Mikrofon am Geraet stummgeschaltet (Headset-Taste oder System)"
+              : muted()
+                ? "Stummschaltung aufheben"
+                : "Stummschalten"
+          }
         >
-          {muted() ? "MIC AUS" : "MIC"}
+          {hardwareMuted() ? "MIC AUS (HW)" : muted() ? "MIC AUS" : "MIC"}
         </button>
         <button
           class={`${styles.controlBtn} ${deafened() ? styles.active : ""}`}
@@ -0,0 +1,121 @@
+import { createSignal, onMount, Show } from "solid-js";
+import { createStore } from "solid-js/store";
+
+import {
+  EventSoundSettings as EventSoundConfig,
+  getEventSoundSettings,
+  playEventSound,
+  setEventSoundSettings,
+  SoundEvent,
+} from "../../bridge";
+import AudioSlider from "./AudioSlider";
+import DspModule from "./DspModule";
+import styles from "./EventSoundSettings.module.css";
+
+const DEFAULT_CONFIG: EventSoundConfig = {
+  volume: 50,
+  maxDucking: 20,
+  join: true,
+  leave: true,
+  poke: true,
+  mention: true,
+  muteToggle: true,
+  soundPackDir: null,
+};
+
+const EVENTS: { key: "join" | "leave" | "poke" | "mention" | "muteToggle"; event: SoundEvent; label: string }[] = [
+  { key: "join", event: "join", label: "Benutzer betritt Kanal" },
+  { key: "leave", event: "leave", label: "Benutzer verlaesst Kanal" },
+  { key: "poke", event: "poke", label: "Angestupst" },
+  { key: "mention", event: "mention", label: "Erwaehnung im Chat" },
+  { key: "muteToggle", event: "mute_toggle", label: "Mikrofon stumm/aktiv" },
+];
+
+export default function EventSoundSettings() {
+  const [config, setConfig] = createStore<EventSoundConfig>({ ...DEFAULT_CONFIG });
+  const [packDir, setPackDir] = createSignal("");
+  const [error, setError] = createSignal<string | null>(null);
+
+  onMount(async () => {
+    try {
+      const loaded = await getEventSoundSettings();
+      setConfig(loaded);
+      setPackDir(loaded.soundPackDir ?? "");
+    } catch {
+      // Backend nicht erreichbar - Standardwerte behalten
+    }
+  });
+
+  const save = async (next: EventSoundConfig) => {
+    try {
+      await setEventSoundSettings(next);
+      setConfig(next);
+      setError(null);
+    } catch (e) {
+      setError(String(e));
+    }
+  };
+
+  const update = <K extends keyof EventSoundConfig>(key: K, value: EventSoundConfig[K]) =>
+    save({ ...config, [key]: value });
+
+  return (
+    <div class={styles.container}>
+      <AudioSlider
+        label="Event-Lautstarke"
+        value={config.volume}
+        min={0}
+        max={100}
+        step={1}
+        unit="%"
+        onChange={(v) => update("volume", v)}
+      />
+      <AudioSlider
+        label="Maximale Sprachabsenkung"
+        value={config.maxDucking}
+        min={0}
+        max={100}
+        step={5}
+        unit="%"
+        onChange={(v) => update("maxDucking", v)}
+      />
+      {EVENTS.map((e) => (
+        <DspModule
+          label={e.label}
+          enabled={config[e.key]}
+          onToggle={(v) => update(e.key, v)}
+        >
+          <button
+            class={styles.packBtn}
+            onClick={async () => { try { await playEventSound(e.event); } catch {} }}
+          >
+            Anhoeren
+          </button>
+        </DspModule>
+      ))}
+      <div class={styles.packRow}>
+        <input
+          class={styles.packInput}
+          type="text"
+          value={packDir()}
+          onInput={(e) => setPackDir(e.currentTarget.value)}
+          placeholder="Eigenes Sound-Pack (Verzeichnis)"
+          aria-label="Sound-Pack-Verzeichnis"
+        />
+        <button
+          class={styles.packBtn}
+          onClick={() => save({ ...config, soundPackDir: packDir().trim() || null })}
+        >
+          Laden
+        </button>
+      </div>
+      <p class={styles.hint}>
+        join, leave, poke, mention, mute_toggle als .wav oder .ogg, je maximal 3 s und 1 MB.
+        Im ersten Moment nach dem Kanalbeitritt und bei deaktiviertem Ton bleiben Sounds stumm.
+      </p>
+      <Show when={error()}>
+        <p class={styles.error}>{error()}</p>
+      </Show>
+    </div>
+  );
+}
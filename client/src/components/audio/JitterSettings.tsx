@@ -1,4 +1,4 @@
-import { JitterConfig } from "../../bridge";
+import { JitterConfig, JitterMode } from "../../bridge";
 import AudioSlider from "./AudioSlider";
 import DspModule from "./DspModule";
 import styles from "./JitterSettings.module.css";
@@ -6,11 +6,36 @@ import styles from "./JitterSettings.module.css";
 interface JitterSettingsProps {
   config: JitterConfig;
   onChange: <K extends keyof JitterConfig>(key: K, value: JitterConfig[K]) => void;
+  onMode: (mode: JitterMode) => void;
 }
 
+const MODE_OPTIONS: { value: JitterMode; label: string; tooltip: string }[] = [
+  {
+    value: "low_latency",
+    label: "Niedrige Latenz",
+    tooltip: "Knappes Budget: spaete Pakete werden lieber verdeckt als abgewartet",
+  },
+  {
+    value: "stability",
+    label: "Stabilitaet",
+    tooltip: "Grosszuegiges Budget fuer instabile Netze, dafuer mehr Verzoegerung",
+  },
+];
+
 export default function JitterSettings(props: JitterSettingsProps) {
   return (
     <div class={styles.container}>
+      <div class={styles.modes} role="group" aria-label="Jitter-Modus">
+        {MODE_OPTIONS.map((opt) => (
+          <button
+            class={styles.modeBtn}
+            onClick={() => props.onMode(opt.value)}
+            title={opt.tooltip}
+          >
+            {opt.label}
+          </button>
+        ))}
+      </div>
       <AudioSlider
         label="Minimaler Puffer"
         value={props.config.minBuffer}
@@ -29,6 +54,15 @@ export default function JitterSettings(props: JitterSettingsProps) {
         unit=" ms"
         onChange={(v) => props.onChange("maxBuffer", v)}
       />
+      <AudioSlider
+        label="Latenzbudget"
+        value={props.config.latencyBudget}
+        min={20}
+        max={500}
+        step={10}
+        unit=" ms"
+        onChange={(v) => props.onChange("latencyBudget", v)}
+      />
       <DspModule
         label="Adaptiver Jitter Buffer"
         enabled={props.config.adaptive}
@@ -1,3 +1,4 @@
+import { Show } from "solid-js";
 import { AudioStats } from "../../bridge";
 import styles from "./LiveMonitor.module.css";
 
@@ -60,8 +61,11 @@ export default function LiveMonitor(props: LiveMonitorProps) {
         <span class={styles.statBadge} title="Latenz">
           {props.stats.latency.total} ms
         </span>
-        <span class={styles.statBadge} title="Paketverlust">
-          PL {props.stats.packetLoss.toFixed(1)}%
+        <span class={styles.statBadge} title="Paketverlust Uplink (Client -> Server)">
+          UL {props.stats.uplinkLoss.toFixed(1)}%
+        </span>
+        <span class={styles.statBadge} title="Paketverlust Downlink (Server -> Client)">
+          DL {props.stats.downlinkLoss.toFixed(1)}%
         </span>
         <span class={styles.statBadge} title="Round-Trip-Time">
           RTT {props.stats.rtt} ms
@@ -69,6 +73,13 @@ export default function LiveMonitor(props: LiveMonitorProps) {
         <span class={styles.statBadge} title="Bitrate">
           {props.stats.bitrate} kbps
         </span>
+        <Show when={props.stats.mos}>
+          {(mos) => (
+            <span class={styles.statBadge} title="Geschaetzte Sprachqualitaet (1 = schlecht, 4.5 = sehr gut)">
+              MOS {mos().toFixed(1)}
+            </span>
+          )}
+        </Show>
       </div>
     </div>
   );
@@ -1,12 +1,19 @@
 import {
   createSignal,
   createResource,
+  onCleanup,
   Show,
 } from "solid-js";
 import type { ChatMessage, ChannelInfo } from "../../bridge";
 import {
   getMessageHistory,
+  onChannelEdited,
+  onChatMessage,
+  onChatMessageDeleted,
+  onChatMessageEdited,
   sendMessage,
+  SlowModeError,
+  streamMessageHistory,
   uploadFile,
 } from "../../bridge";
 import { MessageList } from "./MessageList";
@@ -25,16 +32,52 @@ export function ChatPanel(props: ChatPanelProps) {
   const [dragOver, setDragOver] = createSignal(false);
   let dragCounter = 0;
 
+  // Langsam-Modus: live geaenderte Intervalle und Sperre je Kanal (ms)
+  const [intervalle, setIntervalle] = createSignal<Record<string, number>>({});
+  const [gesperrtBis, setGesperrtBis] = createSignal<Record<string, number>>({});
+  const [jetzt, setJetzt] = createSignal(Date.now());
+  const ticker = setInterval(() => setJetzt(Date.now()), 1000);
+  onCleanup(() => clearInterval(ticker));
+
+  const langsamModus = () => {
+    const ch = props.channel;
+    if (!ch) return 0;
+    return intervalle()[ch.id] ?? ch.slow_mode_secs ?? 0;
+  };
+
+  const restSekunden = () => {
+    const ch = props.channel;
+    const bis = ch ? gesperrtBis()[ch.id] : undefined;
+    if (!bis) return 0;
+    return Math.max(0, Math.ceil((bis - jetzt()) / 1000));
+  };
+
+  const sperren = (channelId: string, sekunden: number) => {
+    const start = Date.now();
+    setJetzt(start);
+    setGesperrtBis((prev) => ({ ...prev, [channelId]: start + sekunden * 1000 }));
+  };
+
   // History laden wenn sich der Kanal aendert
   const [_history] = createResource(
     () => props.channel?.id,
     async (channelId) => {
       if (!channelId) return;
       setError(null);
+      setMessages([]);
       try {
-        const history = await getMessageHistory(channelId, undefined, 50);
-        setMessages(history);
-        setHasMore(history.length === 50);
+        // Teilstuecke sofort anzeigen; nach Kanalwechsel verspaetete verwerfen
+        const abschluss = await streamMessageHistory(
+          channelId,
+          (chunk) => {
+            if (props.channel?.id === channelId) {
+              setMessages((prev) => [...prev, ...chunk]);
+            }
+          },
+          undefined,
+          50
+        );
+        setHasMore(abschluss.next_before !== null);
       } catch (e) {
         setError("Nachrichten konnten nicht geladen werden.");
         console.error(e);
@@ -42,6 +85,41 @@ export function ChatPanel(props: ChatPanelProps) {
     }
   );
 
+  // Nachrichten anderer Kanalmitglieder live einblenden
+  const unlisteners = [
+    onChatMessage((msg) => {
+      if (msg.channel_id === props.channel?.id) {
+        setMessages((prev) => [...prev, msg]);
+      }
+    }),
+    onChatMessageEdited((edit) => {
+      if (edit.channel_id !== props.channel?.id) return;
+      setMessages((prev) =>
+        prev.map((m) =>
+          m.id === edit.id
+            ? { ...m, content: edit.content, edited_at: edit.edited_at }
+            : m
+        )
+      );
+    }),
+    onChatMessageDeleted((deleted) => {
+      if (deleted.channel_id !== props.channel?.id) return;
+      setMessages((prev) => prev.filter((m) => m.id !== deleted.id));
+    }),
+    onChannelEdited((edit) => {
+      setIntervalle((prev) => ({ ...prev, [edit.channel_id]: edit.slow_mode_secs }));
+      // Aufgehobener Langsam-Modus gibt das Eingabefeld sofort frei
+      if (edit.slow_mode_secs === 0) {
+        setGesperrtBis((prev) => ({ ...prev, [edit.channel_id]: 0 }));
+      }
+    }),
+  ];
+  onCleanup(() => {
+    for (const unlisten of unlisteners) {
+      unlisten.then((fn) => fn()).catch(() => {});
+    }
+  });
+
   const handleSend = async (content: string) => {
     const ch = props.channel;
     if (!ch) return;
@@ -49,9 +127,17 @@ export function ChatPanel(props: ChatPanelProps) {
     try {
       const msg = await sendMessage(ch.id, content);
       setMessages((prev) => [...prev, msg]);
+      if (langsamModus() > 0) {
+        sperren(ch.id, langsamModus());
+      }
     } catch (e) {
-      setError("Nachricht konnte nicht gesendet werden.");
-      console.error(e);
+      if (e instanceof SlowModeError) {
+        sperren(ch.id, e.retryAfterSecs);
+      } else {
+        setError("Nachricht konnte nicht gesendet werden.");
+      }
+      // Eingabe behalten, damit sie nach Ablauf erneut gesendet werden kann
+      throw e;
     }
   };
 
@@ -160,6 +246,7 @@ export function ChatPanel(props: ChatPanelProps) {
               channelName={channel().name}
               onSend={handleSend}
               onFileUpload={handleFileUpload}
+              cooldownSecs={restSekunden()}
             />
           </>
         )}
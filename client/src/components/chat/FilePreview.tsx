@@ -1,4 +1,5 @@
 import { Show, createSignal } from "solid-js";
+import { downloadDir, join } from "@tauri-apps/api/path";
 import type { FileInfo } from "../../bridge";
 import { downloadFile } from "../../bridge";
 import styles from "./FilePreview.module.css";
@@ -29,21 +30,20 @@ function fileIcon(mimeType: string): string {
 
 export function FilePreview(props: FilePreviewProps) {
   const [downloading, setDownloading] = createSignal(false);
+  const [fehler, setFehler] = createSignal<string | null>(null);
 
   const handleDownload = async () => {
     if (downloading()) return;
     setDownloading(true);
+    setFehler(null);
     try {
-      const data = await downloadFile(props.fileInfo.id);
-      const blob = new Blob([data.buffer as ArrayBuffer], { type: props.fileInfo.mime_type });
-      const url = URL.createObjectURL(blob);
-      const a = document.createElement("a");
-      a.href = url;
-      a.download = props.fileInfo.filename;
-      a.click();
-      URL.revokeObjectURL(url);
+      const vorschlag = await join(await downloadDir(), props.fileInfo.filename);
+      const ziel = prompt("Speichern unter:", vorschlag);
+      if (!ziel) return;
+      await downloadFile(props.fileInfo.id, ziel);
     } catch (e) {
       console.error("Download fehlgeschlagen:", e);
+      setFehler(String(e));
     } finally {
       setDownloading(false);
     }
@@ -79,6 +79,9 @@ export function FilePreview(props: FilePreviewProps) {
           </div>
         </div>
       </Show>
+      <Show when={fehler()}>
+        <span class={styles.fileSize} role="alert">{fehler()}</span>
+      </Show>
     </div>
   );
 }
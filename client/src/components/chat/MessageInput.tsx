@@ -8,6 +8,8 @@ interface MessageInputProps {
   onSend: (content: string) => Promise<void>;
   onFileUpload: (file: File) => Promise<void>;
   disabled?: boolean;
+  /** Verbleibende Sekunden bis zur naechsten Nachricht (Langsam-Modus) */
+  cooldownSecs?: number;
 }
 
 export function MessageInput(props: MessageInputProps) {
@@ -17,6 +19,8 @@ export function MessageInput(props: MessageInputProps) {
 
   let textareaRef: HTMLTextAreaElement | undefined;
 
+  const gesperrt = () => (props.cooldownSecs ?? 0) > 0;
+
   const handleInput = (e: Event) => {
     const ta = e.target as HTMLTextAreaElement;
     setText(ta.value);
@@ -34,7 +38,7 @@ export function MessageInput(props: MessageInputProps) {
 
   const handleSend = async () => {
     const content = text().trim();
-    if (!content || sending() || props.disabled) return;
+    if (!content || sending() || props.disabled || gesperrt()) return;
 
     setSending(true);
     try {
@@ -68,6 +72,11 @@ export function MessageInput(props: MessageInputProps) {
       <Show when={uploading()}>
         <div class={styles.uploadProgress}>Datei wird hochgeladen...</div>
       </Show>
+      <Show when={gesperrt()}>
+        <div class={styles.cooldown}>
+          Langsam-Modus: naechste Nachricht in {props.cooldownSecs} s
+        </div>
+      </Show>
       <div class={styles.inputBox}>
         <FileUploadButton
           onFileSelected={handleFileSelected}
@@ -86,10 +95,10 @@ export function MessageInput(props: MessageInputProps) {
         <button
           class={styles.sendBtn}
           onClick={handleSend}
-          disabled={!text().trim() || sending() || props.disabled}
-          title="Senden (Enter)"
+          disabled={!text().trim() || sending() || props.disabled || gesperrt()}
+          title={gesperrt() ? "Langsam-Modus aktiv" : "Senden (Enter)"}
         >
-          ➤
+          {gesperrt() ? props.cooldownSecs : "➤"}
         </button>
       </div>
     </div>
@@ -4,6 +4,9 @@ import { editChannel } from "../../bridge";
 import Modal from "../ui/Modal";
 import styles from "./ChannelCreateDialog.module.css";
 
+/** Obergrenze des Servers fuer den Langsam-Modus (6 Stunden) */
+const MAX_LANGSAM_MODUS_SEK = 6 * 60 * 60;
+
 interface ChannelEditDialogProps {
   channel: ChannelInfo & { channel_type?: "permanent" | "semi_permanent" | "temporary" };
   channels: ChannelInfo[];
@@ -16,6 +19,7 @@ export default function ChannelEditDialog(props: ChannelEditDialogProps) {
   const [description, setDescription] = createSignal(props.channel.description ?? "");
   const [password, setPassword] = createSignal("");
   const [maxClients, setMaxClients] = createSignal(props.channel.max_clients ?? 0);
+  const [slowMode, setSlowMode] = createSignal(props.channel.slow_mode_secs ?? 0);
   const [channelType, setChannelType] = createSignal<
     "permanent" | "semi_permanent" | "temporary"
   >(props.channel.channel_type ?? "permanent");
@@ -37,7 +41,8 @@ export default function ChannelEditDialog(props: ChannelEditDialogProps) {
         name().trim(),
         description().trim() || undefined,
         password().trim() || undefined,
-        maxClients() > 0 ? maxClients() : undefined
+        maxClients() > 0 ? maxClients() : undefined,
+        slowMode() !== (props.channel.slow_mode_secs ?? 0) ? slowMode() : undefined
       );
       props.onEdited();
       props.onClose();
@@ -157,6 +162,29 @@ export default function ChannelEditDialog(props: ChannelEditDialogProps) {
               />
             </div>
 
+            {/* Langsam-Modus */}
+            <div class={styles.field}>
+              <label class={styles.label} for="ch-edit-slow">
+                Langsam-Modus <span class={styles.hint}>(Sekunden, 0 = aus)</span>
+              </label>
+              <input
+                id="ch-edit-slow"
+                type="number"
+                class={styles.input}
+                value={slowMode()}
+                onInput={(e) =>
+                  setSlowMode(
+                    Math.min(
+                      MAX_LANGSAM_MODUS_SEK,
+                      Math.max(0, parseInt(e.currentTarget.value) || 0)
+                    )
+                  )
+                }
+                min={0}
+                max={MAX_LANGSAM_MODUS_SEK}
+              />
+            </div>
+
             {/* Parent-Channel (nur Info, nicht aenderbar) */}
             <div class={styles.field}>
               <span class={styles.label}>Parent-Channel</span>
@@ -17,6 +17,7 @@ interface ChannelTreeProps {
   currentUserId: string | null;
   currentUsername?: string | null;
   onChannelJoin: (channelId: string) => void;
+  onChannelExpand?: (channelId: string) => void;
   onChannelSelect: (channel: ChannelNode) => void;
   onChannelEdit?: (channelId: string) => void;
   onChannelDelete?: (channelId: string) => void;
@@ -67,6 +68,14 @@ export function buildChannelTree(channels: ChannelInfo[]): ChannelNode[] {
   return roots;
 }
 
+// Mitglieder nach Sprechaktivitaet: sprechend zuerst, dann zuletzt gesprochen
+export function sortClientsBySpeaking(clients: ClientInfo[]): ClientInfo[] {
+  return [...clients].sort((a, b) => {
+    if (a.is_speaking !== b.is_speaking) return a.is_speaking ? -1 : 1;
+    return (b.last_spoke_at ?? 0) - (a.last_spoke_at ?? 0);
+  });
+}
+
 export default function ChannelTree(props: ChannelTreeProps) {
   const { menuState, show: showMenu, hide: hideMenu } = createContextMenu();
   const [serverCollapsed, setServerCollapsed] = createSignal(false);
@@ -124,6 +133,7 @@ export default function ChannelTree(props: ChannelTreeProps) {
               currentUserId={props.currentUserId}
               currentUsername={props.currentUsername}
               onChannelJoin={props.onChannelJoin}
+              onChannelExpand={props.onChannelExpand}
               onChannelSelect={props.onChannelSelect}
               onChannelEdit={props.onChannelEdit}
               onChannelDelete={props.onChannelDelete}
@@ -160,6 +170,7 @@ interface ChannelBranchProps {
   currentUserId: string | null;
   currentUsername?: string | null;
   onChannelJoin: (channelId: string) => void;
+  onChannelExpand?: (channelId: string) => void;
   onChannelSelect: (channel: ChannelNode) => void;
   onChannelEdit?: (channelId: string) => void;
   onChannelDelete?: (channelId: string) => void;
@@ -173,9 +184,10 @@ interface ChannelBranchProps {
 }
 
 function ChannelBranch(props: ChannelBranchProps) {
-  const [collapsed, setCollapsed] = createSignal(false);
   const ch = props.channel;
-  const hasChildren = () => ch.children.length > 0 || ch.clients.length > 0;
+  // Nicht geladene Unterkanaele: zugeklappt, bis sie nachgeladen sind
+  const [collapsed, setCollapsed] = createSignal(ch.has_children);
+  const hasChildren = () => ch.children.length > 0 || ch.clients.length > 0 || ch.has_children;
   const isCurrent = () => props.currentChannelId === ch.id;
 
   const channelIcon = () => {
@@ -200,10 +212,18 @@ function ChannelBranch(props: ChannelBranchProps) {
 
   const handleToggle = (e: MouseEvent) => {
     e.stopPropagation();
+    if (collapsed() && ch.has_children) {
+      props.onChannelExpand?.(ch.id);
+    }
     setCollapsed((v) => !v);
   };
 
   const handleContextMenu = (e: MouseEvent) => {
+    // Soundboard-Wiedergaben sind keine Benutzer
+    if (c.is_soundboard) {
+      e.preventDefault();
+      return;
+    }
     const items: ContextMenuItem[] = [
       { id: "join", label: "Channel beitreten", icon: "\u25B6", onClick: () => props.onChannelJoin(ch.id) },
       { id: "sep1", label: "", separator: true },
@@ -243,6 +263,13 @@ function ChannelBranch(props: ChannelBranchProps) {
         {/* Channel-Name */}
         <span class={styles.channelName}>{ch.name}</span>
 
+        {/* Notfall-Stummschaltung */}
+        <Show when={ch.emergency_muted}>
+          <span class={styles.emergencyBanner} title="Notfall-Stummschaltung: nur Moderatoren werden gehoert">
+            Stumm
+          </span>
+        </Show>
+
         {/* Client-Anzahl */}
         <Show when={ch.max_clients > 0}>
           <span class={styles.clientCount}>
@@ -254,7 +281,7 @@ function ChannelBranch(props: ChannelBranchProps) {
       {/* Kinder (Clients + Subchannels) */}
       <Show when={!collapsed()}>
         {/* Clients im Channel */}
-        <For each={ch.clients}>
+        <For each={sortClientsBySpeaking(ch.clients)}>
           {(client) => (
             <ClientEntry
               client={client}
@@ -283,6 +310,7 @@ function ChannelBranch(props: ChannelBranchProps) {
               currentUserId={props.currentUserId}
               currentUsername={props.currentUsername}
               onChannelJoin={props.onChannelJoin}
+              onChannelExpand={props.onChannelExpand}
               onChannelSelect={props.onChannelSelect}
               onChannelEdit={props.onChannelEdit}
               onChannelDelete={props.onChannelDelete}
@@ -364,7 +392,7 @@ function ClientEntry(props: ClientEntryProps) {
 
   return (
     <div
-      class={`${styles.clientRow} ${props.isSelf ? styles.selfClient : ""}`}
+      class={`${styles.clientRow} ${props.isSelf ? styles.selfClient : ""} ${c.is_speaking ? styles.speaking : ""} ${c.is_emergency_muted ? styles.emergencyMuted : ""}`}
       style={{ "padding-left": `${24 + props.depth * 16}px` }}
       onContextMenu={handleContextMenu}
     >
@@ -375,6 +403,16 @@ function ClientEntry(props: ClientEntryProps) {
           <span class={styles.selfLabel}> (Du)</span>
         </Show>
       </span>
+      <Show when={c.is_listener}>
+        <span class={styles.listenerBadge} title="Nur Zuhoeren">
+          Zuhoerer
+        </span>
+      </Show>
+      <Show when={c.is_soundboard}>
+        <span class={styles.listenerBadge} title="Vom Server abgespielter Sound">
+          Soundboard
+        </span>
+      </Show>
     </div>
   );
 }
@@ -0,0 +1,144 @@
+import { createSignal, For, type JSX } from "solid-js";
+import type { Motd } from "../../bridge";
+import styles from "./MotdDialog.module.css";
+
+interface MotdDialogProps {
+  motd: Motd;
+  /** `remember` = bis zur naechsten Aenderung nicht erneut anzeigen */
+  onClose: (remember: boolean) => void;
+}
+
+const SAFE_LINK = /^(https?|mailto):/i;
+const INLINE =
+  /(`+)([\s\S]*?)\1|\*\*(.+?)\*\*|__(.+?)__|\*(.+?)\*|_(.+?)_|\[([^\]]*)\]\(([^)\s]+)(?:\s+"[^"]*")?\)/;
+
+/** Text ohne Markdown: Server-Maskierung und Backslash-Escapes aufheben */
+function plain(text: string): string {
+  return text.replace(/&lt;/g, "<").replace(/\\([!-/:-@[-`{-~])/g, "$1");
+}
+
+function inline(text: string): JSX.Element[] {
+  const out: JSX.Element[] = [];
+  let rest = text;
+  while (rest.length > 0) {
+    const m = INLINE.exec(rest);
+    if (!m) {
+      out.push(plain(rest));
+      break;
+    }
+    if (m.index > 0) out.push(plain(rest.slice(0, m.index)));
+    if (m[1] !== undefined) {
+      out.push(<code>{m[2]}</code>);
+    } else if (m[3] !== undefined || m[4] !== undefined) {
+      out.push(<strong>{inline(m[3] ?? m[4])}</strong>);
+    } else if (m[5] !== undefined || m[6] !== undefined) {
+      out.push(<em>{inline(m[5] ?? m[6])}</em>);
+    } else if (SAFE_LINK.test(m[8])) {
+      out.push(
+        <a href={m[8]} target="_blank" rel="noopener noreferrer">
+          {inline(m[7])}
+        </a>
+      );
+    } else {
+      out.push(inline(m[7]));
+    }
+    rest = rest.slice(m.index + m[0].length);
+  }
+  return out;
+}
+
+/** Rendert die vom Server erlaubte Markdown-Teilmenge ohne rohes HTML */
+function render(markdown: string): JSX.Element[] {
+  const blocks: JSX.Element[] = [];
+  const lines = markdown.split("\n");
+  let i = 0;
+  while (i < lines.length) {
+    const line = lines[i];
+    const fence = line.trimStart().slice(0, 3);
+    if (fence === "```" || fence === "~~~") {
+      const code: string[] = [];
+      i++;
+      while (i < lines.length && !lines[i].trimStart().startsWith(fence)) {
+        code.push(lines[i++]);
+      }
+      i++;
+      blocks.push(
+        <pre class={styles.code}>
+          <code>{code.join("\n")}</code>
+        </pre>
+      );
+      continue;
+    }
+    const heading = /^(#{1,6})\s+(.*)$/.exec(line);
+    if (heading) {
+      blocks.push(<h3 class={styles.heading}>{inline(heading[2])}</h3>);
+      i++;
+      continue;
+    }
+    const listItem = /^\s*(?:[-*+]|\d+[.)])\s+/;
+    if (listItem.test(line)) {
+      const items: string[] = [];
+      while (i < lines.length && listItem.test(lines[i])) {
+        items.push(lines[i++].replace(listItem, ""));
+      }
+      blocks.push(
+        <ul class={styles.list}>
+          <For each={items}>{(item) => <li>{inline(item)}</li>}</For>
+        </ul>
+      );
+      continue;
+    }
+    if (line.startsWith(">")) {
+      const quote: string[] = [];
+      while (i < lines.length && lines[i].startsWith(">")) {
+        quote.push(lines[i++].replace(/^>\s?/, ""));
+      }
+      blocks.push(<blockquote class={styles.quote}>{render(quote.join("\n"))}</blockquote>);
+      continue;
+    }
+    if (line.trim() === "") {
+      i++;
+      continue;
+    }
+    const paragraph: JSX.Element[] = [];
+    while (
+      i < lines.length &&
+      lines[i].trim() !== "" &&
+      !/^(#{1,6}\s|>|\s*(```|~~~))/.test(lines[i]) &&
+      !listItem.test(lines[i])
+    ) {
+      if (paragraph.length > 0) paragraph.push(<br />);
+      paragraph.push(...inline(lines[i++]));
+    }
+    blocks.push(<p class={styles.paragraph}>{paragraph}</p>);
+  }
+  return blocks;
+}
+
+export default function MotdDialog(props: MotdDialogProps) {
+  const [remember, setRemember] = createSignal(true);
+
+  return (
+    <div class={styles.overlay}>
+      <div class={styles.dialog}>
+        <div class={styles.header}>
+          <span class={styles.title}>Nachricht des Tages</span>
+        </div>
+        <div class={styles.body}>{render(props.motd.markdown)}</div>
+        <div class={styles.actions}>
+          <label class={styles.remember}>
+            <input
+              type="checkbox"
+              checked={remember()}
+              onChange={(e) => setRemember(e.currentTarget.checked)}
+            />
+            Erst nach einer Aenderung wieder anzeigen
+          </label>
+          <button class={styles.btnPrimary} onClick={() => props.onClose(remember())}>
+            Schliessen
+          </button>
+        </div>
+      </div>
+    </div>
+  );
+}
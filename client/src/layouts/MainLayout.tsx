@@ -1,9 +1,40 @@
 import type { RouteSectionProps } from "@solidjs/router";
+import { onCleanup, onMount } from "solid-js";
 import Titlebar from "../components/Titlebar";
 import Statusbar from "../components/Statusbar";
+import { reportActivity } from "../bridge";
+import { restoreUserAudio } from "../utils/userAudio";
 import styles from "./MainLayout.module.css";
 
+// Aktivitaetsmeldungen fuer die AFK-Erkennung hoechstens einmal pro Minute
+const AKTIVITAET_INTERVALL_MS = 60_000;
+const AKTIVITAET_EREIGNISSE = ["keydown", "pointerdown", "pointermove", "wheel"] as const;
+
 export default function MainLayout(props: RouteSectionProps) {
+  let letzteMeldung = 0;
+
+  const aktivitaetMelden = () => {
+    const jetzt = Date.now();
+    if (jetzt - letzteMeldung < AKTIVITAET_INTERVALL_MS) return;
+    letzteMeldung = jetzt;
+    reportActivity().catch(() => {});
+  };
+
+  onMount(() => {
+    restoreUserAudio().catch((e) =>
+      console.warn("Benutzer-Lautstaerken konnten nicht geladen werden:", e)
+    );
+    for (const ereignis of AKTIVITAET_EREIGNISSE) {
+      window.addEventListener(ereignis, aktivitaetMelden, { passive: true });
+    }
+  });
+
+  onCleanup(() => {
+    for (const ereignis of AKTIVITAET_EREIGNISSE) {
+      window.removeEventListener(ereignis, aktivitaetMelden);
+    }
+  });
+
   return (
     <div class={styles.root}>
       <Titlebar />
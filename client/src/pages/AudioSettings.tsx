@@ -14,6 +14,8 @@ import {
   AudioSettingsConfig,
   AudioStats,
   CalibrationResult,
+  JitterMode,
+  applyJitterMode,
   getAudioDevices,
   getAudioSettings,
   getAudioStats,
@@ -47,6 +49,7 @@ import CalibrationModal from "../components/audio/CalibrationModal";
 import PttKeyCapture from "../components/audio/PttKeyCapture";
 import CodecSettings from "../components/audio/CodecSettings";
 import JitterSettings from "../components/audio/JitterSettings";
+import EventSoundSettings from "../components/audio/EventSoundSettings";
 import CustomSelect from "../components/ui/CustomSelect";
 
 import styles from "./AudioSettings.module.css";
@@ -70,6 +73,7 @@ const DEFAULT_SETTINGS: AudioSettingsConfig = {
     application: "voip",
     fec: true,
     dtx: true,
+    dtxKeepaliveMs: 400,
     channels: "mono",
   },
   dsp: {
@@ -79,7 +83,7 @@ const DEFAULT_SETTINGS: AudioSettingsConfig = {
     echoCancellation: { enabled: true, tailLength: 100 },
     deesser: { enabled: false, frequency: 7000, threshold: -20, ratio: 4 },
   },
-  jitter: { minBuffer: 20, maxBuffer: 100, adaptive: true },
+  jitter: { minBuffer: 20, maxBuffer: 100, adaptive: true, latencyBudget: 250 },
 };
 
 const DEFAULT_STATS: AudioStats = {
@@ -89,9 +93,21 @@ const DEFAULT_STATS: AudioStats = {
   noiseFloor: -60,
   isClipping: false,
   latency: { device: 0, encoding: 0, jitter: 0, network: 0, total: 0 },
-  packetLoss: 0,
+  uplinkLoss: 0,
+  downlinkLoss: 0,
   rtt: 0,
   bitrate: 0,
+  playbackUnderruns: 0,
+  mos: null,
+  codecErrors: {
+    invalidFrameSize: 0,
+    invalidPacket: 0,
+    bufferTooSmall: 0,
+    internalOpusError: 0,
+    lastOpusCode: null,
+    framesPadded: 0,
+    decoderResets: 0,
+  },
 };
 
 const NOISE_LEVELS = ["off", "low", "medium", "high"] as const;
@@ -211,6 +227,14 @@ export default function AudioSettings() {
     setSettings("jitter", key, value);
   }
 
+  async function applyJitterPreset(mode: JitterMode) {
+    try {
+      setSettings("jitter", await applyJitterMode(mode));
+    } catch (e) {
+      console.error("Jitter-Modus konnte nicht gesetzt werden:", e);
+    }
+  }
+
   function selectPreset(id: "speech" | "balanced" | "music" | "low_bandwidth") {
     const map: Record<string, Partial<AudioSettingsConfig["codec"]>> = {
       speech: { bitrate: 32, sampleRate: 16000, channels: "mono", fec: true, dtx: true },
@@ -500,6 +524,14 @@ export default function AudioSettings() {
           </div>
         </section>
 
+        {/* ---- Event-Sounds ---- */}
+        <section class={styles.section}>
+          <h2 class={styles.sectionTitle}>Benachrichtigungstoene</h2>
+          <div class={styles.sectionBody}>
+            <EventSoundSettings />
+          </div>
+        </section>
+
         {/* ---- Auto-Setup ---- */}
         <section class={styles.section}>
           <h2 class={styles.sectionTitle}>Automatisches Einrichten</h2>
@@ -694,7 +726,11 @@ export default function AudioSettings() {
           <section class={styles.section}>
             <h2 class={styles.sectionTitle}>Jitter Buffer</h2>
             <div class={styles.sectionBody}>
-              <JitterSettings config={settings.jitter} onChange={updateJitter} />
+              <JitterSettings
+                config={settings.jitter}
+                onChange={updateJitter}
+                onMode={applyJitterPreset}
+              />
             </div>
           </section>
 
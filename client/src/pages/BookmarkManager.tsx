@@ -3,6 +3,11 @@ import { getCurrentWindow } from "@tauri-apps/api/window";
 import type { Bookmark } from "../components/ui/MenuBar";
 import { loadProfiles, type SoundProfile } from "../utils/soundProfiles";
 import CustomSelect from "../components/ui/CustomSelect";
+import {
+  getBookmarkLatencies,
+  refreshBookmarkLatencies,
+  type LatencyMeasurement,
+} from "../bridge";
 import styles from "./BookmarkManager.module.css";
 
 const BOOKMARKS_KEY = "speakeasy-bookmarks";
@@ -21,11 +26,37 @@ export default function BookmarkManager() {
   const [bookmarks, setBookmarks] = createSignal<Bookmark[]>(loadBookmarks());
   const [editing, setEditing] = createSignal<EditState | null>(null);
   const [soundProfiles, setSoundProfiles] = createSignal<SoundProfile[]>([]);
+  const [latencies, setLatencies] = createSignal<Record<string, LatencyMeasurement>>({});
+  const [measuring, setMeasuring] = createSignal(false);
 
   onMount(() => {
     setSoundProfiles(loadProfiles());
+    getBookmarkLatencies().then(setLatencies).catch(() => {});
+    refreshLatencies();
   });
 
+  async function refreshLatencies() {
+    if (measuring() || bookmarks().length === 0) return;
+    setMeasuring(true);
+    try {
+      const targets = bookmarks().map((bm) => ({ address: bm.address, port: bm.port }));
+      const result = await refreshBookmarkLatencies(targets);
+      setLatencies((prev) => ({ ...prev, ...result }));
+    } catch (e) {
+      console.error("Latenzmessung fehlgeschlagen:", e);
+    } finally {
+      setMeasuring(false);
+    }
+  }
+
+  function latencyLabel(bm: Bookmark): string {
+    const m = latencies()[`${bm.address}:${bm.port}`];
+    if (!m) return measuring() ? "..." : "";
+    if (m.medianMs === null) return "keine Antwort";
+    const loss = m.verlust > 0 ? ` (${Math.round(m.verlust * 100)}% Verlust)` : "";
+    return `${Math.round(m.medianMs)} ms${loss}`;
+  }
+
   function loadBookmarks(): Bookmark[] {
     try {
       const stored = localStorage.getItem(BOOKMARKS_KEY);
@@ -109,6 +140,7 @@ export default function BookmarkManager() {
               <th>Benutzer</th>
               <th>Passwort</th>
               <th>Sound-Profil</th>
+              <th>Latenz</th>
               <th></th>
             </tr>
           </thead>
@@ -182,6 +214,7 @@ export default function BookmarkManager() {
                           ariaLabel="Sound-Profil"
                         />
                       </td>
+                      <td>{latencyLabel(bm)}</td>
                       <td>
                         <div class={styles.actions}>
                           <button class={styles.btnSave} onClick={saveEdit}>
@@ -204,6 +237,7 @@ export default function BookmarkManager() {
                       {bm.password ? "••••••" : ""}
                     </td>
                     <td>{profileName(bm.soundProfileId)}</td>
+                    <td>{latencyLabel(bm)}</td>
                     <td>
                       <div class={styles.actions}>
                         <button class={styles.btnEdit} onClick={() => startEdit(i())}>
@@ -223,6 +257,13 @@ export default function BookmarkManager() {
       </Show>
 
       <div class={styles.footer}>
+        <button
+          class={styles.btnEdit}
+          onClick={refreshLatencies}
+          disabled={measuring() || bookmarks().length === 0}
+        >
+          Latenz messen
+        </button>
         <button class={styles.btnClose} onClick={handleClose}>
           Schliessen
         </button>
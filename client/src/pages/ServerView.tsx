@@ -1,7 +1,7 @@
 import { createSignal, createEffect, onCleanup, onMount, Show } from "solid-js";
 import { useParams, useNavigate } from "@solidjs/router";
 import { WebviewWindow } from "@tauri-apps/api/webviewWindow";
-import { getServerInfo, joinChannel, disconnect, connectToServer, getCurrentUsername, type ChannelInfo } from "../bridge";
+import { getServerInfo, joinChannel, expandChannel, disconnect, connectToServer, getCurrentUsername, onPresenceChanged, onClientSpeaking, onSessionRenewalFailed, type ChannelInfo, type Motd } from "../bridge";
 import ChannelTree, { buildChannelTree, type ChannelNode } from "../components/server/ChannelTree";
 import ChannelInfoPanel from "../components/server/ChannelInfo";
 import ServerInfoPanel from "../components/server/ServerInfoPanel";
@@ -12,9 +12,11 @@ import ChannelCreateDialog from "../components/server/ChannelCreateDialog";
 import ChannelEditDialog from "../components/server/ChannelEditDialog";
 import ChannelDeleteDialog from "../components/server/ChannelDeleteDialog";
 import ConnectDialog from "../components/server/ConnectDialog";
+import MotdDialog from "../components/server/MotdDialog";
+import { acknowledgeMotd, isMotdPending } from "../utils/motd";
 import {
   getTabs, getActiveTabId, getActiveTab, setActiveTab,
-  addTab, removeTab, updateTab, reorderTabs,
+  addTab, removeTab, updateTab, reorderTabs, isCapabilityMissing,
 } from "../stores/connectionStore";
 import styles from "./ServerView.module.css";
 
@@ -41,12 +43,19 @@ export default function ServerView() {
   const [currentChannelId, setCurrentChannelId] = createSignal<string | null>(null);
   const [chatVisible, setChatVisible] = createSignal(false);
   const [error, setError] = createSignal<string | null>(null);
+  const [joinError, setJoinError] = createSignal<string | null>(null);
   const [loading, setLoading] = createSignal(false);
   const [dialog, setDialog] = createSignal<DialogState>({ type: "none" });
   const [infoPanelMode, setInfoPanelMode] = createSignal<InfoPanelMode>("server");
   const [currentUsername, setCurrentUsername] = createSignal<string | null>(null);
   const [connected, setConnected] = createSignal(false);
   const [showConnectDialog, setShowConnectDialog] = createSignal(false);
+  const [motd, setMotd] = createSignal<Motd | null>(null);
+  // In dieser Sitzung geschlossen, ohne die Version zu bestaetigen
+  let motdDismissed = 0;
+
+  const motdServer = () =>
+    `${localStorage.getItem("speakeasy_last_address") || ""}:${Number(localStorage.getItem("speakeasy_last_port")) || 9001}`;
 
   onMount(async () => {
     try {
@@ -86,6 +95,9 @@ export default function ServerView() {
       setUptimeSecs(info.uptime_secs);
       setRawChannels(info.channels);
       setChannels(buildChannelTree(info.channels));
+      if (isMotdPending(motdServer(), info.motd) && info.motd.version > motdDismissed) {
+        setMotd(info.motd);
+      }
       setError(null);
       setLoading(false);
       // Tab-Name mit dem echten Servernamen aktualisieren
@@ -117,14 +129,79 @@ export default function ServerView() {
     if (pollTimer) clearInterval(pollTimer);
   });
 
+  // An-/Abmeldungen und Kanalwechsel anderer sofort zeigen, nicht erst beim
+  // naechsten Abruf (eigene Wechsel laden handleChannelJoin & Co. selbst)
+  const unlistenPresence = onPresenceChanged(() => {
+    if (connected()) fetchServerInfo();
+  });
+  onCleanup(() => {
+    unlistenPresence.then((unlisten) => unlisten()).catch(() => {});
+  });
+
+  // Sprechanzeige sofort umschalten; der naechste Abruf bestaetigt den Stand
+  const unlistenSpeaking = onClientSpeaking((ev) => {
+    const updated = rawChannels().map((ch) =>
+      ch.id !== ev.channel_id
+        ? ch
+        : {
+            ...ch,
+            clients: ch.clients.map((c) =>
+              c.id !== ev.user_id
+                ? c
+                : {
+                    ...c,
+                    is_speaking: ev.speaking,
+                    last_spoke_at: ev.speaking ? c.last_spoke_at : Date.now(),
+                  }
+            ),
+          }
+    );
+    setRawChannels(updated);
+    setChannels(buildChannelTree(updated));
+  });
+  onCleanup(() => {
+    unlistenSpeaking.then((unlisten) => unlisten()).catch(() => {});
+  });
+
+  // Session abgelaufen und nicht mehr verlaengerbar: trennen und neu anmelden lassen
+  const unlistenRenewal = onSessionRenewalFailed(async (ev) => {
+    console.warn("Session-Verlaengerung gescheitert:", ev.message);
+    try {
+      await disconnect();
+    } catch {
+      // Verbindung ist ohnehin nicht mehr nutzbar
+    }
+    if (pollTimer) clearInterval(pollTimer);
+    setConnected(false);
+    resetViewState();
+    updateTab(getActiveTabId(), { connected: false });
+    setShowConnectDialog(true);
+  });
+  onCleanup(() => {
+    unlistenRenewal.then((unlisten) => unlisten()).catch(() => {});
+  });
+
   const handleChannelJoin = async (channelId: string) => {
     try {
       await joinChannel(channelId);
+      setJoinError(null);
       setCurrentChannelId(channelId);
       // Sofort Server-Info aktualisieren damit der Wechsel instant sichtbar ist
       fetchServerInfo();
     } catch (e) {
       console.error("Kanal beitreten fehlgeschlagen:", e);
+      // z.B. fehlender Sprachcodec: Meldung enthaelt den Hinweis zur Abhilfe
+      setJoinError(String(e));
+    }
+  };
+
+  // Grosse Server: Unterkanaele erst beim Aufklappen laden
+  const handleChannelExpand = async (channelId: string) => {
+    try {
+      await expandChannel(channelId);
+      fetchServerInfo();
+    } catch (e) {
+      console.error("Unterkanaele laden fehlgeschlagen:", e);
     }
   };
 
@@ -445,6 +522,9 @@ export default function ServerView() {
       <Show when={connected()}>
         <Show when={!loading()} fallback={<div class={styles.loading}>Lade Serverinfo...</div>}>
           <Show when={!error()} fallback={<div class={styles.error}>{error()}</div>}>
+            <Show when={joinError()}>
+              <div class={styles.error}>{joinError()}</div>
+            </Show>
             {/* Hauptbereich: ChannelTree links + Info rechts */}
             <div class={styles.mainArea}>
               {/* Channel-Baum (links) */}
@@ -455,6 +535,7 @@ export default function ServerView() {
                   currentUserId={null}
                   currentUsername={currentUsername()}
                   onChannelJoin={handleChannelJoin}
+                  onChannelExpand={handleChannelExpand}
                   onChannelSelect={handleChannelSelect}
                   onChannelEdit={handleChannelEdit}
                   onChannelDelete={handleChannelDelete}
@@ -491,24 +572,42 @@ export default function ServerView() {
               </Show>
             </div>
 
-            {/* Chat-Panel (unten, einklappbar) */}
-            <div class={styles.chatToggle}>
-              <button class={styles.chatToggleBtn} onClick={toggleChat}>
-                {chatVisible() ? "Chat ausblenden" : "Chat einblenden"}
-                <span class={styles.chatShortcut}>Strg+Enter</span>
-              </button>
-            </div>
-
-            <Show when={chatVisible()}>
-              <div class={styles.chatArea}>
-                <ChatPanel channel={activeChatChannel()} />
+            {/* Chat-Panel (unten, einklappbar; nur wenn der Server Chat anbietet) */}
+            <Show when={!isCapabilityMissing("chat")}>
+              <div class={styles.chatToggle}>
+                <button class={styles.chatToggleBtn} onClick={toggleChat}>
+                  {chatVisible() ? "Chat ausblenden" : "Chat einblenden"}
+                  <span class={styles.chatShortcut}>Strg+Enter</span>
+                </button>
               </div>
+
+              <Show when={chatVisible()}>
+                <div class={styles.chatArea}>
+                  <ChatPanel channel={activeChatChannel()} />
+                </div>
+              </Show>
             </Show>
           </Show>
         </Show>
       </Show>
 
-      {/* ConnectDialog (Modal) */}
+      {/* Nachricht des Tages (Modal) */}
+      <Show when={motd()}>
+        {(current) => (
+          <MotdDialog
+            motd={current()}
+            onClose={(remember) => {
+              if (remember) {
+                acknowledgeMotd(motdServer(), current().version);
+              }
+              motdDismissed = current().version;
+              setMotd(null);
+            }}
+          />
+        )}
+      </Show>
+
+            {/* ConnectDialog (Modal) */}
       <Show when={showConnectDialog()}>
         <ConnectDialog
           onClose={() => setShowConnectDialog(false)}
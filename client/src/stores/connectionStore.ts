@@ -1,4 +1,5 @@
 import { createSignal } from "solid-js";
+import { onCapabilitiesDegraded } from "../bridge";
 
 export interface ConnectionTab {
   id: string;
@@ -16,6 +17,12 @@ const [tabs, setTabs] = createSignal<ConnectionTab[]>([
 ]);
 const [activeTabId, setActiveTabId] = createSignal("default");
 
+// Vom aktuellen Server nicht angebotene Funktionen (z.B. "chat")
+const [missingCapabilities, setMissingCapabilities] = createSignal<string[]>([]);
+onCapabilitiesDegraded((e) => setMissingCapabilities(e.missing)).catch(() => {});
+
+export function isCapabilityMissing(name: string): boolean { return missingCapabilities().includes(name); }
+
 export function getTabs(): ConnectionTab[] { return tabs(); }
 export function getActiveTabId(): string { return activeTabId(); }
 export function getActiveTab(): ConnectionTab | undefined { return tabs().find(t => t.id === activeTabId()); }
@@ -0,0 +1,27 @@
+import type { Motd } from "../bridge";
+
+const STORAGE_KEY = "speakeasy-motd-acknowledged";
+
+/** Zuletzt bestaetigte MOTD-Version je Server (`adresse:port`) */
+type Acknowledged = Record<string, number>;
+
+function load(): Acknowledged {
+  try {
+    const raw = localStorage.getItem(STORAGE_KEY);
+    return raw ? JSON.parse(raw) : {};
+  } catch {
+    return {};
+  }
+}
+
+/** Ob die MOTD angezeigt werden soll (neuer als die bestaetigte Version) */
+export function isMotdPending(server: string, motd: Motd | null): motd is Motd {
+  return motd !== null && motd.version > (load()[server] ?? 0);
+}
+
+/** Merkt sich die Version: erst eine neuere wird wieder angezeigt */
+export function acknowledgeMotd(server: string, version: number): void {
+  const acknowledged = load();
+  acknowledged[server] = Math.max(acknowledged[server] ?? 0, version);
+  localStorage.setItem(STORAGE_KEY, JSON.stringify(acknowledged));
+}
@@ -0,0 +1,55 @@
+import {
+  importSettings,
+  resetUserAudioPreferences,
+  setUserAudioPreferences,
+  setUserMuted,
+  setUserVolume,
+  type SettingsExport,
+  type UserAudioPreferences,
+} from "../bridge";
+
+const STORAGE_KEY = "speakeasy-user-audio";
+
+export function loadUserAudio(): UserAudioPreferences | null {
+  try {
+    const raw = localStorage.getItem(STORAGE_KEY);
+    return raw ? JSON.parse(raw) : null;
+  } catch {
+    return null;
+  }
+}
+
+export function saveUserAudio(prefs: UserAudioPreferences): void {
+  localStorage.setItem(STORAGE_KEY, JSON.stringify(prefs));
+}
+
+/** Reicht die gespeicherte Tabelle an das Backend (beim Start) */
+export async function restoreUserAudio(): Promise<void> {
+  const stored = loadUserAudio();
+  if (!stored) return;
+  saveUserAudio(await setUserAudioPreferences(stored));
+}
+
+export async function changeUserVolume(userId: string, gain: number): Promise<void> {
+  saveUserAudio(await setUserVolume(userId, gain));
+}
+
+export async function changeUserMuted(userId: string, muted: boolean): Promise<void> {
+  saveUserAudio(await setUserMuted(userId, muted));
+}
+
+export async function resetUserAudio(userId?: string): Promise<void> {
+  saveUserAudio(await resetUserAudioPreferences(userId));
+}
+
+/** Importiert Einstellungen und speichert die Benutzer-Lautstaerken mit */
+export async function importSettingsWithUserAudio(
+  settings: SettingsExport,
+  includeUserAudio: boolean
+): Promise<SettingsExport> {
+  const result = await importSettings(settings, includeUserAudio);
+  if (includeUserAudio && result.userAudio) {
+    saveUserAudio(result.userAudio);
+  }
+  return result;
+}
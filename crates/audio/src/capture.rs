@@ -2,14 +2,24 @@
 //!
 //! Oeffnet einen cpal InputStream und schreibt Samples in einen
 //! lock-free Ring-Buffer. Die Verarbeitung laeuft im cpal-Callback.
-
-use cpal::traits::{DeviceTrait, StreamTrait};
-use cpal::{Device, SampleFormat, Stream, StreamConfig};
-use ringbuf::traits::{Producer, Split};
-use ringbuf::{HeapCons, HeapProd, HeapRb};
-use tracing::{debug, error, warn};
-
-use crate::error::{AudioError, AudioResult};
+//! Geraete mit i16-, u16- oder u8-Samples werden dort nach f32 gewandelt
+//! (siehe [`crate::sample`]).
+//!
+//! Ohne Feature `hardware` bleiben nur Konfiguration und Ring-Buffer-Typen;
+//! als Quelle dient dann eine [`AudioSource`](crate::quelle::AudioSource).
+
+use ringbuf::{HeapCons, HeapProd};
+
+#[cfg(feature = "hardware")]
+use {
+    crate::error::{AudioError, AudioResult},
+    crate::sample::{self, GeraeteSample},
+    cpal::traits::{DeviceTrait, StreamTrait},
+    cpal::{Device, SampleFormat, SizedSample, Stream, StreamConfig},
+    ringbuf::traits::{Producer, Split},
+    ringbuf::HeapRb,
+    tracing::{debug, error, warn},
+};
 
 /// Konfiguration fuer den Audio-Capture
 #[derive(Debug, Clone)]
@@ -41,11 +51,13 @@ pub type CaptureConsumer = HeapCons<f32>;
 ///
 /// Haelt den cpal-Stream am Leben. Wird der CaptureStream gedroppt,
 /// stoppt die Aufnahme automatisch.
+#[cfg(feature = "hardware")]
 pub struct CaptureStream {
     _stream: Stream,
     config: CaptureConfig,
 }
 
+#[cfg(feature = "hardware")]
 impl CaptureStream {
     /// Gibt die Konfiguration des Streams zurueck
     pub fn config(&self) -> &CaptureConfig {
@@ -57,6 +69,7 @@ impl CaptureStream {
 ///
 /// Gibt den Stream und den Ring-Buffer Consumer zurueck.
 /// Der Producer laeuft im cpal-Callback-Thread.
+#[cfg(feature = "hardware")]
 pub fn open_capture_stream(
     device: &Device,
     config: CaptureConfig,
@@ -68,72 +81,40 @@ pub fn open_capture_stream(
     };
 
     let rb = HeapRb::<f32>::new(config.buffer_size);
-    let (mut producer, consumer) = rb.split();
+    let (producer, consumer) = rb.split();
 
-    // cpal-Callback: schreibt Samples in den Ring-Buffer
-    let err_fn = |err| error!("Capture-Fehler: {}", err);
-
-    // Unterstuetzte Sample-Formate pruefen
-    let supported = device
+    // Unterstuetzte Sample-Formate bei der gewuenschten Rate pruefen
+    let angebote: Vec<SampleFormat> = device
         .supported_input_configs()
         .map_err(|e| AudioError::StreamFehler(e.to_string()))?
-        .find(|c| {
+        .filter(|c| {
             c.min_sample_rate().0 <= config.sample_rate
                 && c.max_sample_rate().0 >= config.sample_rate
                 && c.channels() >= config.channels
-        });
-
-    let sample_format = supported
+        })
         .map(|c| c.sample_format())
-        .unwrap_or(SampleFormat::F32);
+        .collect();
+    let nativ = device
+        .default_input_config()
+        .ok()
+        .map(|c| c.sample_format());
+
+    let sample_format = if angebote.is_empty() {
+        SampleFormat::F32
+    } else {
+        sample::format_waehlen(nativ, &angebote).ok_or_else(|| {
+            AudioError::StreamFehler(format!(
+                "Nicht unterstuetztes Sample-Format: {:?}",
+                angebote
+            ))
+        })?
+    };
 
     let stream = match sample_format {
-        SampleFormat::F32 => device
-            .build_input_stream(
-                &stream_config,
-                move |data: &[f32], _| {
-                    let written = producer.push_slice(data);
-                    if written < data.len() {
-                        warn!(
-                            "Capture Ring-Buffer voll, {} Samples verworfen",
-                            data.len() - written
-                        );
-                    }
-                },
-                err_fn,
-                None,
-            )
-            .map_err(|e| AudioError::StreamFehler(e.to_string()))?,
-        SampleFormat::I16 => device
-            .build_input_stream(
-                &stream_config,
-                move |data: &[i16], _| {
-                    let floats: Vec<f32> =
-                        data.iter().map(|&s| s as f32 / i16::MAX as f32).collect();
-                    let written = producer.push_slice(&floats);
-                    if written < floats.len() {
-                        warn!("Capture Ring-Buffer voll");
-                    }
-                },
-                err_fn,
-                None,
-            )
-            .map_err(|e| AudioError::StreamFehler(e.to_string()))?,
-        SampleFormat::U8 => device
-            .build_input_stream(
-                &stream_config,
-                move |data: &[u8], _| {
-                    let floats: Vec<f32> =
-                        data.iter().map(|&s| (s as f32 - 128.0) / 128.0).collect();
-                    let written = producer.push_slice(&floats);
-                    if written < floats.len() {
-                        warn!("Capture Ring-Buffer voll");
-                    }
-                },
-                err_fn,
-                None,
-            )
-            .map_err(|e| AudioError::StreamFehler(e.to_string()))?,
+        SampleFormat::F32 => eingabe_bauen::<f32>(device, &stream_config, producer)?,
+        SampleFormat::I16 => eingabe_bauen::<i16>(device, &stream_config, producer)?,
+        SampleFormat::U16 => eingabe_bauen::<u16>(device, &stream_config, producer)?,
+        SampleFormat::U8 => eingabe_bauen::<u8>(device, &stream_config, producer)?,
         _ => {
             return Err(AudioError::StreamFehler(format!(
                 "Nicht unterstuetztes Sample-Format: {:?}",
@@ -147,8 +128,8 @@ pub fn open_capture_stream(
         .map_err(|e| AudioError::StreamFehler(e.to_string()))?;
 
     debug!(
-        "Capture-Stream geoeffnet: {}Hz {}ch",
-        config.sample_rate, config.channels
+        "Capture-Stream geoeffnet: {}Hz {}ch {:?}",
+        config.sample_rate, config.channels, sample_format
     );
 
     Ok((
@@ -160,10 +141,41 @@ pub fn open_capture_stream(
     ))
 }
 
+/// Baut den Input-Stream fuer Geraete-Samples vom Typ `T`
+#[cfg(feature = "hardware")]
+fn eingabe_bauen<T>(
+    device: &Device,
+    stream_config: &StreamConfig,
+    mut producer: CaptureProducer,
+) -> AudioResult<Stream>
+where
+    T: GeraeteSample + SizedSample,
+{
+    let err_fn = |err| error!("Capture-Fehler: {}", err);
+    let mut floats: Vec<f32> = Vec::new();
+
+    device
+        .build_input_stream(
+            stream_config,
+            move |data: &[T], _| {
+                sample::in_f32(data, &mut floats);
+                let written = producer.push_slice(&floats);
+                if written < floats.len() {
+                    warn!(
+                        "Capture Ring-Buffer voll, {} Samples verworfen",
+                        floats.len() - written
+                    );
+                }
+            },
+            err_fn,
+            None,
+        )
+        .map_err(|e| AudioError::StreamFehler(e.to_string()))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use cpal::traits::HostTrait;
 
     #[test]
     fn capture_config_default() {
@@ -174,8 +186,11 @@ mod tests {
     }
 
     #[test]
+    #[cfg(feature = "hardware")]
     #[ignore = "Benoetigt Audio-Hardware"]
     fn capture_stream_oeffnen() {
+        use cpal::traits::HostTrait;
+
         let host = cpal::default_host();
         if let Some(device) = host.default_input_device() {
             let config = CaptureConfig::default();
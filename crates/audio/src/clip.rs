@@ -0,0 +1,147 @@
+//! Kurze Audio-Clips fuer das Soundboard
+//!
+//! Soundboard-Sounds werden einmal beim Hochladen kodiert und danach nur
+//! noch als fertige Opus-Frames in einen Kanal eingespeist. Der Server muss
+//! so beim Abspielen weder dekodieren noch neu kodieren; er taktet die
+//! Frames lediglich im 20-ms-Raster aus.
+
+use std::time::Duration;
+
+use speakeasy_protocol::codec::{
+    ChannelCount, FrameSizeMs, OpusApplication, OpusConfig, SampleRate,
+};
+
+use crate::codec::OpusEncoder;
+use crate::error::{AudioError, AudioResult};
+use crate::quelle::wav_dekodieren;
+
+/// Dauer eines Clip-Frames
+pub const CLIP_FRAME_MS: u32 = 20;
+
+/// Fertig kodierter Clip
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KodierterClip {
+    /// Opus-Frames zu je [`CLIP_FRAME_MS`] in Abspielreihenfolge
+    pub frames: Vec<Vec<u8>>,
+    /// Gesamtdauer
+    pub dauer: Duration,
+}
+
+/// Encoder-Konfiguration fuer Clips: Vollband, Mono, ohne FEC und DTX
+///
+/// Clips sind oft Musik oder Effekte, daher `Audio` statt `Voip`. Stille
+/// wird nicht unterdrueckt, damit die Taktung beim Abspielen gleichmaessig
+/// bleibt.
+pub fn clip_config() -> OpusConfig {
+    OpusConfig {
+        bitrate_kbps: 64,
+        sample_rate: SampleRate::Hz48000,
+        channels: ChannelCount::Mono,
+        frame_size: FrameSizeMs::Ms20,
+        application: OpusApplication::Audio,
+        fec_enabled: false,
+        dtx_enabled: false,
+        complexity: 9,
+        vbr_enabled: true,
+    }
+}
+
+/// Kodiert eine WAV-Datei im Speicher zu Clip-Frames
+///
+/// Siehe [`clip_kodieren`]; Format und Abtastrate der Datei sind beliebig.
+pub fn wav_clip_kodieren(daten: &[u8], max_dauer: Duration) -> AudioResult<KodierterClip> {
+    clip_kodieren(&wav_dekodieren(daten)?, max_dauer)
+}
+
+/// Kodiert 48-kHz-Mono-Samples zu Clip-Frames
+///
+/// Der letzte Frame wird mit Stille aufgefuellt. Leere Clips und Clips
+/// laenger als `max_dauer` werden abgelehnt.
+pub fn clip_kodieren(samples: &[f32], max_dauer: Duration) -> AudioResult<KodierterClip> {
+    if samples.is_empty() {
+        return Err(AudioError::Konfiguration("Clip enthaelt kein Audio".into()));
+    }
+
+    let mut encoder = OpusEncoder::new(clip_config())?;
+    let frame_size = encoder.frame_size();
+    let anzahl = samples.len().div_ceil(frame_size);
+    let dauer = Duration::from_millis(anzahl as u64 * CLIP_FRAME_MS as u64);
+    if dauer > max_dauer {
+        return Err(AudioError::Konfiguration(format!(
+            "Clip ist {} ms lang, erlaubt sind hoechstens {} ms",
+            dauer.as_millis(),
+            max_dauer.as_millis()
+        )));
+    }
+
+    let mut frames = Vec::with_capacity(anzahl);
+    let mut puffer = vec![0.0f32; frame_size];
+    for block in samples.chunks(frame_size) {
+        puffer[..block.len()].copy_from_slice(block);
+        puffer[block.len()..].fill(0.0);
+        frames.push(encoder.encode(&puffer)?);
+    }
+
+    Ok(KodierterClip { frames, dauer })
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::codec::OpusDecoder;
+
+    fn sinus(anzahl: usize) -> Vec<f32> {
+        (0..anzahl)
+            .map(|i| (i as f32 * 440.0 * std::f32::consts::TAU / 48000.0).sin() * 0.5)
+            .collect()
+    }
+
+    #[test]
+    fn letzter_frame_wird_aufgefuellt() {
+        // 50 ms -> drei 20-ms-Frames
+        let clip = clip_kodieren(&sinus(2400), Duration::from_secs(1)).unwrap();
+        assert_eq!(clip.frames.len(), 3);
+        assert_eq!(clip.dauer, Duration::from_millis(60));
+
+        let mut decoder = OpusDecoder::new(SampleRate::Hz48000, ChannelCount::Mono).unwrap();
+        for frame in &clip.frames {
+            assert!(!frame.is_empty());
+            assert_eq!(decoder.decode(frame).unwrap().len(), 960);
+        }
+    }
+
+    #[test]
+    fn zu_lange_und_leere_clips_werden_abgelehnt() {
+        let zu_lang = clip_kodieren(&sinus(48000), Duration::from_millis(500));
+        assert!(matches!(zu_lang, Err(AudioError::Konfiguration(_))));
+        let leer = clip_kodieren(&[], Duration::from_secs(1));
+        assert!(matches!(leer, Err(AudioError::Konfiguration(_))));
+    }
+
+    #[test]
+    fn wav_aus_dem_speicher() {
+        let spec = hound::WavSpec {
+            channels: 1,
+            sample_rate: 24000,
+            bits_per_sample: 16,
+            sample_format: hound::SampleFormat::Int,
+        };
+        let mut daten = std::io::Cursor::new(Vec::new());
+        let mut writer = hound::WavWriter::new(&mut daten, spec).unwrap();
+        // 100 ms bei 24 kHz
+        for i in 0..2400 {
+            writer.write_sample(((i % 48) as i16 - 24) * 500).unwrap();
+        }
+        writer.finalize().unwrap();
+
+        let clip = wav_clip_kodieren(daten.get_ref(), Duration::from_secs(1)).unwrap();
+        assert_eq!(clip.frames.len(), 5);
+
+        let kaputt = wav_clip_kodieren(b"keine wav-datei", Duration::from_secs(1));
+        assert!(matches!(kaputt, Err(AudioError::Konfiguration(_))));
+    }
+}
@@ -2,17 +2,202 @@
 //!
 //! Kapselt audiopus und stellt eine einfache f32-PCM basierte API bereit.
 //! Nutzt OpusConfig aus speakeasy-protocol fuer Konfiguration.
+//!
+//! Fuer Systeme ohne funktionierendes libopus gibt es einen PCMU-Fallback
+//! (G.711 mu-law, 8 kHz). [`SprachEncoder`] und [`SprachDecoder`] waehlen
+//! die Implementierung anhand des ausgehandelten [`AudioCodec`].
+//!
+//! Codec-Fehler tragen eine [`CodecFehlerArt`]; [`CodecZaehler`] zaehlt sie
+//! je Kategorie fuer Statistik und Diagnose. [`frame_angleichen`] prueft
+//! Frames vor dem Encoder auf die ausgehandelte Groesse.
 
 use audiopus::{
     coder::{Decoder, Encoder},
     Application, Channels, SampleRate,
 };
+use std::fmt;
+use std::sync::atomic::{AtomicI32, AtomicU32, AtomicU64, Ordering};
+use std::sync::Arc;
 use tracing::debug;
 
 use crate::error::{AudioError, AudioResult};
 use speakeasy_protocol::codec::{
-    ChannelCount, FrameSizeMs, OpusApplication, OpusConfig, SampleRate as ProtocolSampleRate,
+    AudioPreset, ChannelCount, FrameSizeMs, OpusApplication, OpusConfig,
+    SampleRate as ProtocolSampleRate,
 };
+use speakeasy_protocol::voice::AudioCodec;
+
+/// Erwarteter Paketverlust in Prozent, fuer den Opus FEC-Daten einplant
+const FEC_VERLUST_PROZENT: i32 = 10;
+
+/// libopus-Fehlercode fuer ungueltige Argumente (`OPUS_BAD_ARG`)
+const OPUS_BAD_ARG: i32 = -1;
+
+// ---------------------------------------------------------------------------
+// Fehlerkategorien und Zaehler
+// ---------------------------------------------------------------------------
+
+/// Kategorie eines Codec-Fehlers
+///
+/// Unterscheidet fuer den Support, ob falsch zugeschnittene Frames,
+/// kaputte Pakete oder ein gestoerter Decoder-Zustand hinter
+/// Aussetzern stecken.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CodecFehlerArt {
+    /// Frame hat nicht die ausgehandelte Anzahl Samples
+    UngueltigeFrameGroesse,
+    /// Paket ist leer oder verletzt das Opus-Format
+    UngueltigesPaket,
+    /// Ausgabepuffer zu klein fuer das Ergebnis
+    PufferZuKlein,
+    /// Sonstiger Fehler aus libopus mit dessen (negativem) Fehlercode
+    OpusIntern(i32),
+}
+
+impl CodecFehlerArt {
+    fn aus_opus(e: &audiopus::Error) -> Self {
+        use audiopus::{Error, ErrorCode};
+        match e {
+            Error::Opus(ErrorCode::InvalidPacket) | Error::EmptyPacket | Error::PacketTooLarge => {
+                Self::UngueltigesPaket
+            }
+            Error::Opus(ErrorCode::BufferTooSmall) => Self::PufferZuKlein,
+            Error::SignalsTooLarge => Self::UngueltigeFrameGroesse,
+            Error::Opus(code) => Self::OpusIntern(*code as i32),
+            // Von audiopus abgelehnte Parameter (Bitrate, Kanaele, ...)
+            _ => Self::OpusIntern(OPUS_BAD_ARG),
+        }
+    }
+}
+
+impl fmt::Display for CodecFehlerArt {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UngueltigeFrameGroesse => f.write_str("ungueltige Frame-Groesse"),
+            Self::UngueltigesPaket => f.write_str("ungueltiges Paket"),
+            Self::PufferZuKlein => f.write_str("Puffer zu klein"),
+            Self::OpusIntern(code) => write!(f, "interner Opus-Fehler {code}"),
+        }
+    }
+}
+
+/// Wandelt einen audiopus-Fehler in einen kategorisierten [`AudioError`]
+fn opus_fehler(e: audiopus::Error) -> AudioError {
+    AudioError::CodecFehler {
+        art: CodecFehlerArt::aus_opus(&e),
+        grund: e.to_string(),
+    }
+}
+
+fn frame_groesse_fehler(erwartet: usize, erhalten: usize) -> AudioError {
+    AudioError::CodecFehler {
+        art: CodecFehlerArt::UngueltigeFrameGroesse,
+        grund: format!("PCM-Frame muss {erwartet} Samples lang sein, war {erhalten}"),
+    }
+}
+
+/// Momentaufnahme eines [`CodecZaehler`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CodecStatistik {
+    pub ungueltige_frame_groesse: u64,
+    pub ungueltiges_paket: u64,
+    pub puffer_zu_klein: u64,
+    pub opus_intern: u64,
+    /// Fehlercode des letzten internen Opus-Fehlers
+    pub letzter_opus_code: Option<i32>,
+    /// Zu kurze Frames, die vor dem Encoder mit Stille aufgefuellt wurden
+    pub frames_aufgefuellt: u64,
+    /// Nach wiederholten Dekodierfehlern neu erstellte Decoder
+    pub decoder_resets: u64,
+}
+
+/// Codec-Fehler je Kategorie, lock-free zwischen Audio-Threads und
+/// Statistik geteilt
+#[derive(Debug, Clone, Default)]
+pub struct CodecZaehler(Arc<CodecZaehlerInnen>);
+
+#[derive(Debug, Default)]
+struct CodecZaehlerInnen {
+    ungueltige_frame_groesse: AtomicU64,
+    ungueltiges_paket: AtomicU64,
+    puffer_zu_klein: AtomicU64,
+    opus_intern: AtomicU64,
+    /// 0 = noch kein interner Fehler (libopus-Codes sind negativ)
+    letzter_opus_code: AtomicI32,
+    frames_aufgefuellt: AtomicU64,
+    decoder_resets: AtomicU64,
+}
+
+impl CodecZaehler {
+    /// Zaehlt einen Fehler seiner Kategorie nach
+    pub fn erfassen(&self, art: CodecFehlerArt) {
+        let zaehler = match art {
+            CodecFehlerArt::UngueltigeFrameGroesse => &self.0.ungueltige_frame_groesse,
+            CodecFehlerArt::UngueltigesPaket => &self.0.ungueltiges_paket,
+            CodecFehlerArt::PufferZuKlein => &self.0.puffer_zu_klein,
+            CodecFehlerArt::OpusIntern(code) => {
+                self.0.letzter_opus_code.store(code, Ordering::Relaxed);
+                &self.0.opus_intern
+            }
+        };
+        zaehler.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Zaehlt `e`, falls es ein Codec-Fehler ist
+    pub fn fehler_erfassen(&self, e: &AudioError) {
+        if let Some(art) = e.codec_art() {
+            self.erfassen(art);
+        }
+    }
+
+    pub fn statistik(&self) -> CodecStatistik {
+        let laden = |z: &AtomicU64| z.load(Ordering::Relaxed);
+        CodecStatistik {
+            ungueltige_frame_groesse: laden(&self.0.ungueltige_frame_groesse),
+            ungueltiges_paket: laden(&self.0.ungueltiges_paket),
+            puffer_zu_klein: laden(&self.0.puffer_zu_klein),
+            opus_intern: laden(&self.0.opus_intern),
+            letzter_opus_code: match self.0.letzter_opus_code.load(Ordering::Relaxed) {
+                0 => None,
+                code => Some(code),
+            },
+            frames_aufgefuellt: laden(&self.0.frames_aufgefuellt),
+            decoder_resets: laden(&self.0.decoder_resets),
+        }
+    }
+
+    fn aufgefuellt(&self) {
+        self.0.frames_aufgefuellt.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn decoder_reset(&self) {
+        self.0.decoder_resets.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// Bringt einen Frame vor dem Encoder auf die ausgehandelte Groesse
+///
+/// Zu kurze Frames (z.B. durch falsches Sammeln der Samples) werden mit
+/// Stille aufgefuellt, zu lange abgelehnt – Opus wuerde sie sonst still
+/// falsch kodieren. Beides wird in `zaehler` erfasst.
+pub fn frame_angleichen(
+    samples: &mut Vec<f32>,
+    frame_size: usize,
+    zaehler: &CodecZaehler,
+) -> AudioResult<()> {
+    match samples.len() {
+        n if n == frame_size => Ok(()),
+        n if n < frame_size => {
+            samples.resize(frame_size, 0.0);
+            zaehler.aufgefuellt();
+            Ok(())
+        }
+        n => {
+            zaehler.erfassen(CodecFehlerArt::UngueltigeFrameGroesse);
+            Err(frame_groesse_fehler(frame_size, n))
+        }
+    }
+}
 
 /// Opus-Encoder: kodiert f32-PCM zu Opus-Bytes
 pub struct OpusEncoder {
@@ -30,30 +215,32 @@ impl OpusEncoder {
         let channels = protocol_channels_to_audiopus(config.channels);
         let application = protocol_app_to_audiopus(config.application);
 
-        let mut encoder = Encoder::new(sample_rate, channels, application)
-            .map_err(|e| AudioError::CodecFehler(e.to_string()))?;
+        let mut encoder = Encoder::new(sample_rate, channels, application).map_err(opus_fehler)?;
 
         // Bitrate setzen
         encoder
             .set_bitrate(audiopus::Bitrate::BitsPerSecond(
                 (config.bitrate_kbps as i32) * 1000,
             ))
-            .map_err(|e| AudioError::CodecFehler(e.to_string()))?;
+            .map_err(opus_fehler)?;
 
         // Komplexitaet setzen (audiopus 0.2 erwartet u8)
         encoder
             .set_complexity(config.complexity)
-            .map_err(|e| AudioError::CodecFehler(e.to_string()))?;
+            .map_err(opus_fehler)?;
 
         // VBR
-        encoder
-            .set_vbr(config.vbr_enabled)
-            .map_err(|e| AudioError::CodecFehler(e.to_string()))?;
+        encoder.set_vbr(config.vbr_enabled).map_err(opus_fehler)?;
 
         // FEC
         encoder
             .set_inband_fec(config.fec_enabled)
-            .map_err(|e| AudioError::CodecFehler(e.to_string()))?;
+            .map_err(opus_fehler)?;
+        if config.fec_enabled {
+            // Ohne erwarteten Verlust legt Opus keine FEC-Daten bei
+            // OPUS_SET_PACKET_LOSS_PERC_REQUEST = 4014
+            let _ = encoder.set_encoder_ctl_request(4014, FEC_VERLUST_PROZENT);
+        }
 
         // DTX: audiopus 0.2 hat kein set_dtx – wird ueber set_prediction_disabled angenähert
         // DTX-Effekt: bei sehr leisem Signal Pakete nicht senden (hier via encoder-ctl)
@@ -81,11 +268,7 @@ impl OpusEncoder {
     /// Die Eingabe muss exakt `frame_size()` Samples lang sein.
     pub fn encode(&mut self, pcm: &[f32]) -> AudioResult<Vec<u8>> {
         if pcm.len() != self.frame_size {
-            return Err(AudioError::Konfiguration(format!(
-                "PCM-Frame muss {} Samples lang sein, war {}",
-                self.frame_size,
-                pcm.len()
-            )));
+            return Err(frame_groesse_fehler(self.frame_size, pcm.len()));
         }
 
         // Puffer: max. 4000 Bytes reicht fuer alle Opus-Frames
@@ -93,12 +276,31 @@ impl OpusEncoder {
         let written = self
             .encoder
             .encode_float(pcm, &mut output)
-            .map_err(|e| AudioError::CodecFehler(e.to_string()))?;
+            .map_err(opus_fehler)?;
 
         output.truncate(written);
         Ok(output)
     }
 
+    /// Stellt die Bitrate im laufenden Betrieb um
+    ///
+    /// Der Encoder-Zustand bleibt erhalten; der naechste Frame wird bereits
+    /// mit der neuen Bitrate kodiert.
+    pub fn set_bitrate(&mut self, bitrate_kbps: u16) -> AudioResult<()> {
+        let mut config = self.config.clone();
+        config.bitrate_kbps = bitrate_kbps;
+        config.validieren().map_err(AudioError::Konfiguration)?;
+
+        self.encoder
+            .set_bitrate(audiopus::Bitrate::BitsPerSecond(
+                (bitrate_kbps as i32) * 1000,
+            ))
+            .map_err(opus_fehler)?;
+        self.config = config;
+        debug!("OpusEncoder: Bitrate auf {}kbps umgestellt", bitrate_kbps);
+        Ok(())
+    }
+
     /// Gibt die erwartete Frame-Groesse in Samples zurueck
     pub fn frame_size(&self) -> usize {
         self.frame_size
@@ -124,7 +326,7 @@ impl OpusDecoder {
         let sr = protocol_rate_to_audiopus(sample_rate)?;
         let ch = protocol_channels_to_audiopus(channels);
 
-        let decoder = Decoder::new(sr, ch).map_err(|e| AudioError::CodecFehler(e.to_string()))?;
+        let decoder = Decoder::new(sr, ch).map_err(opus_fehler)?;
 
         // Standardmaessig 20ms Frame-Groesse
         let frame_size = FrameSizeMs::Ms20.samples_per_frame(sample_rate) as usize;
@@ -150,12 +352,21 @@ impl OpusDecoder {
     }
 
     /// Dekodiert Opus-Bytes zu f32-PCM
+    ///
+    /// Pakete mit mehr Samples als `frame_size()` scheitern mit
+    /// [`CodecFehlerArt::PufferZuKlein`].
     pub fn decode(&mut self, opus_data: &[u8]) -> AudioResult<Vec<f32>> {
+        if opus_data.is_empty() {
+            return Err(AudioError::CodecFehler {
+                art: CodecFehlerArt::UngueltigesPaket,
+                grund: "leeres Opus-Paket".into(),
+            });
+        }
         let mut output = vec![0.0f32; self.frame_size * self.channels as usize];
         let decoded = self
             .decoder
             .decode_float(Some(opus_data), &mut output, false)
-            .map_err(|e| AudioError::CodecFehler(e.to_string()))?;
+            .map_err(opus_fehler)?;
 
         output.truncate(decoded * self.channels as usize);
         Ok(output)
@@ -167,7 +378,23 @@ impl OpusDecoder {
         let decoded = self
             .decoder
             .decode_float(None::<&[u8]>, &mut output, false)
-            .map_err(|e| AudioError::CodecFehler(e.to_string()))?;
+            .map_err(opus_fehler)?;
+
+        output.truncate(decoded * self.channels as usize);
+        Ok(output)
+    }
+
+    /// Rekonstruiert den verlorenen Frame vor `naechstes` aus dessen
+    /// In-Band-FEC-Daten
+    ///
+    /// Danach muss `naechstes` selbst noch normal dekodiert werden. Enthaelt
+    /// das Paket keine FEC-Daten, liefert Opus eine PLC-Schaetzung.
+    pub fn decode_fec(&mut self, naechstes: &[u8]) -> AudioResult<Vec<f32>> {
+        let mut output = vec![0.0f32; self.frame_size * self.channels as usize];
+        let decoded = self
+            .decoder
+            .decode_float(Some(naechstes), &mut output, true)
+            .map_err(opus_fehler)?;
 
         output.truncate(decoded * self.channels as usize);
         Ok(output)
@@ -187,6 +414,286 @@ impl OpusDecoder {
     pub fn sample_rate(&self) -> ProtocolSampleRate {
         self.sample_rate
     }
+
+    /// Verwirft den Decoder-Zustand (neuer libopus-Decoder)
+    ///
+    /// Fuer Streams, deren Pakete nach einem gestoerten Zustand dauerhaft
+    /// scheitern; Frame-Groesse und Format bleiben.
+    pub fn zuruecksetzen(&mut self) -> AudioResult<()> {
+        let sr = protocol_rate_to_audiopus(self.sample_rate)?;
+        let ch = protocol_channels_to_audiopus(self.channels);
+        self.decoder = Decoder::new(sr, ch).map_err(opus_fehler)?;
+        debug!("OpusDecoder zurueckgesetzt");
+        Ok(())
+    }
+}
+
+// ---------------------------------------------------------------------------
+// PCMU (G.711 mu-law)
+// ---------------------------------------------------------------------------
+
+/// Verhaeltnis der Pipeline-Abtastrate (48 kHz) zur PCMU-Abtastrate (8 kHz)
+const PCMU_FAKTOR: usize = 6;
+/// Frame-Groesse der Pipeline: 20ms bei 48 kHz
+const PCMU_FRAME_SIZE: usize = 960;
+/// Bias und Begrenzung nach G.711
+const MULAW_BIAS: i32 = 0x84;
+const MULAW_CLIP: i32 = 32635;
+
+/// PCMU-Encoder: 48-kHz-Frames werden auf 8 kHz reduziert und mu-law kodiert
+///
+/// Ein 20ms-Frame ergibt 160 Bytes. Braucht keine externe Bibliothek.
+#[derive(Debug, Default)]
+pub struct PcmuEncoder;
+
+impl PcmuEncoder {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Kodiert einen PCM-Frame (48 kHz Mono, `frame_size()` Samples)
+    pub fn encode(&mut self, pcm: &[f32]) -> AudioResult<Vec<u8>> {
+        if pcm.len() != PCMU_FRAME_SIZE {
+            return Err(frame_groesse_fehler(PCMU_FRAME_SIZE, pcm.len()));
+        }
+        Ok(pcm
+            .chunks_exact(PCMU_FAKTOR)
+            .map(|gruppe| {
+                // Mittelwert als einfacher Tiefpass vor dem Dezimieren
+                let mittel = gruppe.iter().sum::<f32>() / PCMU_FAKTOR as f32;
+                mulaw_kodieren((mittel.clamp(-1.0, 1.0) * i16::MAX as f32) as i16)
+            })
+            .collect())
+    }
+
+    pub fn frame_size(&self) -> usize {
+        PCMU_FRAME_SIZE
+    }
+}
+
+/// PCMU-Decoder: mu-law-Bytes zu 48-kHz-PCM (lineare Interpolation)
+#[derive(Debug, Default)]
+pub struct PcmuDecoder {
+    /// Letztes Sample des vorherigen Pakets (Start der Interpolation)
+    letztes: f32,
+}
+
+impl PcmuDecoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Dekodiert PCMU-Bytes zu f32-PCM (48 kHz Mono)
+    pub fn decode(&mut self, pcmu_data: &[u8]) -> AudioResult<Vec<f32>> {
+        let mut output = Vec::with_capacity(pcmu_data.len() * PCMU_FAKTOR);
+        for &byte in pcmu_data {
+            let ziel = mulaw_dekodieren(byte) as f32 / i16::MAX as f32;
+            for schritt in 1..=PCMU_FAKTOR {
+                let anteil = schritt as f32 / PCMU_FAKTOR as f32;
+                output.push(self.letztes + (ziel - self.letztes) * anteil);
+            }
+            self.letztes = ziel;
+        }
+        Ok(output)
+    }
+
+    /// Ersatz fuer ein verlorenes Paket: ein Frame Stille
+    pub fn decode_plc(&mut self) -> AudioResult<Vec<f32>> {
+        self.letztes = 0.0;
+        Ok(vec![0.0; PCMU_FRAME_SIZE])
+    }
+
+    pub fn zuruecksetzen(&mut self) {
+        self.letztes = 0.0;
+    }
+}
+
+fn mulaw_kodieren(sample: i16) -> u8 {
+    let mut wert = sample as i32;
+    let vorzeichen = if wert < 0 {
+        wert = -wert;
+        0x80
+    } else {
+        0x00
+    };
+    wert = wert.min(MULAW_CLIP) + MULAW_BIAS;
+    // Durch den Bias ist Bit 7 immer gesetzt: Exponent 0..=7
+    let exponent = (31 - wert.leading_zeros() as i32 - 7).clamp(0, 7);
+    let mantisse = (wert >> (exponent + 3)) & 0x0F;
+    !(vorzeichen | (exponent << 4) as u8 | mantisse as u8)
+}
+
+fn mulaw_dekodieren(byte: u8) -> i16 {
+    let byte = !byte;
+    let exponent = ((byte >> 4) & 0x07) as i32;
+    let mantisse = (byte & 0x0F) as i32;
+    let betrag = (((mantisse << 3) + MULAW_BIAS) << exponent) - MULAW_BIAS;
+    if byte & 0x80 != 0 {
+        -betrag as i16
+    } else {
+        betrag as i16
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Ausgehandelter Codec
+// ---------------------------------------------------------------------------
+
+/// Encoder fuer den ausgehandelten Codec
+pub enum SprachEncoder {
+    Opus(OpusEncoder),
+    Pcmu(PcmuEncoder),
+}
+
+impl SprachEncoder {
+    /// Erstellt den Encoder; fuer Opus gilt `config`
+    pub fn new(codec: AudioCodec, config: OpusConfig) -> AudioResult<Self> {
+        match codec {
+            AudioCodec::Opus => OpusEncoder::new(config).map(Self::Opus),
+            AudioCodec::Pcmu => Ok(Self::Pcmu(PcmuEncoder::new())),
+        }
+    }
+
+    pub fn codec(&self) -> AudioCodec {
+        match self {
+            Self::Opus(_) => AudioCodec::Opus,
+            Self::Pcmu(_) => AudioCodec::Pcmu,
+        }
+    }
+
+    pub fn encode(&mut self, pcm: &[f32]) -> AudioResult<Vec<u8>> {
+        match self {
+            Self::Opus(enc) => enc.encode(pcm),
+            Self::Pcmu(enc) => enc.encode(pcm),
+        }
+    }
+
+    pub fn frame_size(&self) -> usize {
+        match self {
+            Self::Opus(enc) => enc.frame_size(),
+            Self::Pcmu(enc) => enc.frame_size(),
+        }
+    }
+
+    /// Enthalten die Pakete In-Band-FEC-Daten (Flag `VoiceFlags::FEC`)?
+    pub fn fec_aktiv(&self) -> bool {
+        match self {
+            Self::Opus(enc) => enc.config().fec_enabled,
+            Self::Pcmu(_) => false,
+        }
+    }
+
+    /// Aktuelle Bitrate (kbps); PCMU hat eine feste Rate und liefert `None`
+    pub fn bitrate_kbps(&self) -> Option<u16> {
+        match self {
+            Self::Opus(enc) => Some(enc.config().bitrate_kbps),
+            Self::Pcmu(_) => None,
+        }
+    }
+
+    /// Stellt die Bitrate um; fuer PCMU ohne Wirkung
+    pub fn set_bitrate(&mut self, bitrate_kbps: u16) -> AudioResult<()> {
+        match self {
+            Self::Opus(enc) => enc.set_bitrate(bitrate_kbps),
+            Self::Pcmu(_) => Ok(()),
+        }
+    }
+}
+
+/// Vom Server vorgegebene Ziel-Bitrate fuer einen laufenden Encoder
+///
+/// Die Signaling-Seite schreibt die Vorgabe in einen geteilten `AtomicU32`
+/// (0 = keine Vorgabe). Die Sende-Schleife ruft vor jedem Frame
+/// [`BitrateVorgabe::anwenden`] auf und stellt den Encoder ohne Neustart
+/// des Streams um, begrenzt auf die Grenzen des Audio-Presets.
+pub struct BitrateVorgabe {
+    ziel: Arc<AtomicU32>,
+    preset: AudioPreset,
+    /// Zuletzt uebernommene Vorgabe (unbegrenzt)
+    uebernommen: u32,
+}
+
+impl BitrateVorgabe {
+    pub fn neu(ziel: Arc<AtomicU32>, preset: AudioPreset) -> Self {
+        Self {
+            ziel,
+            preset,
+            uebernommen: 0,
+        }
+    }
+
+    /// Uebernimmt eine geaenderte Vorgabe in den Encoder
+    ///
+    /// Gibt die neu eingestellte Bitrate zurueck, `None` wenn sich nichts
+    /// geaendert hat.
+    pub fn anwenden(&mut self, encoder: &mut SprachEncoder) -> AudioResult<Option<u16>> {
+        let ziel = self.ziel.load(Ordering::Relaxed);
+        if ziel == 0 || ziel == self.uebernommen {
+            return Ok(None);
+        }
+        self.uebernommen = ziel;
+
+        let kbps = self
+            .preset
+            .bitrate_begrenzen(u16::try_from(ziel).unwrap_or(u16::MAX));
+        if encoder.bitrate_kbps().is_none_or(|aktuell| aktuell == kbps) {
+            return Ok(None);
+        }
+        encoder.set_bitrate(kbps)?;
+        Ok(Some(kbps))
+    }
+}
+
+/// Decoder fuer den Codec eines eingehenden Streams
+pub enum SprachDecoder {
+    Opus(OpusDecoder),
+    Pcmu(PcmuDecoder),
+}
+
+impl SprachDecoder {
+    /// Erstellt den Decoder; `config` wird wie beim Encoder geprueft
+    pub fn new(codec: AudioCodec, config: &OpusConfig) -> AudioResult<Self> {
+        match codec {
+            AudioCodec::Opus => {
+                config.validieren().map_err(AudioError::Konfiguration)?;
+                OpusDecoder::from_config(config).map(Self::Opus)
+            }
+            AudioCodec::Pcmu => Ok(Self::Pcmu(PcmuDecoder::new())),
+        }
+    }
+
+    pub fn decode(&mut self, data: &[u8]) -> AudioResult<Vec<f32>> {
+        match self {
+            Self::Opus(dec) => dec.decode(data),
+            Self::Pcmu(dec) => dec.decode(data),
+        }
+    }
+
+    pub fn decode_plc(&mut self) -> AudioResult<Vec<f32>> {
+        match self {
+            Self::Opus(dec) => dec.decode_plc(),
+            Self::Pcmu(dec) => dec.decode_plc(),
+        }
+    }
+
+    /// Verlorener Frame vor `naechstes`: FEC bei Opus, sonst PLC
+    pub fn decode_fec(&mut self, naechstes: &[u8]) -> AudioResult<Vec<f32>> {
+        match self {
+            Self::Opus(dec) => dec.decode_fec(naechstes),
+            Self::Pcmu(dec) => dec.decode_plc(),
+        }
+    }
+
+    /// Verwirft den Decoder-Zustand
+    pub fn zuruecksetzen(&mut self) -> AudioResult<()> {
+        match self {
+            Self::Opus(dec) => dec.zuruecksetzen(),
+            Self::Pcmu(dec) => {
+                dec.zuruecksetzen();
+                Ok(())
+            }
+        }
+    }
 }
 
 // ---------------------------------------------------------------------------
@@ -221,7 +728,7 @@ fn protocol_app_to_audiopus(app: OpusApplication) -> Application {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use speakeasy_protocol::codec::{AudioPreset, ChannelCount, SampleRate as PSR};
+    use speakeasy_protocol::codec::SampleRate as PSR;
 
     #[test]
     fn encoder_konfiguration_speech() {
@@ -273,8 +780,98 @@ mod tests {
         let config = AudioPreset::Speech.config();
         let mut enc = OpusEncoder::new(config).unwrap();
         // 320 Samples erwartet, aber 100 uebergeben
-        let result = enc.encode(&vec![0.0f32; 100]);
-        assert!(result.is_err());
+        let fehler = enc.encode(&vec![0.0f32; 100]).unwrap_err();
+        assert_eq!(
+            fehler.codec_art(),
+            Some(CodecFehlerArt::UngueltigeFrameGroesse)
+        );
+        let fehler = PcmuEncoder::new().encode(&[0.0; 961]).unwrap_err();
+        assert_eq!(
+            fehler.codec_art(),
+            Some(CodecFehlerArt::UngueltigeFrameGroesse)
+        );
+    }
+
+    #[test]
+    fn kaputte_pakete_werden_kategorisiert() {
+        let config = AudioPreset::Balanced.config();
+        let mut dec = OpusDecoder::from_config(&config).unwrap();
+        let zaehler = CodecZaehler::default();
+
+        // Leer; Code 3 ohne Frame-Anzahl; Code 3 mit 0 Frames;
+        // Code 2 mit Frame-Laenge hinter dem Paketende
+        for paket in [&[][..], &[0xFF], &[0x03, 0x00], &[0x02, 0xFB]] {
+            let fehler = dec.decode(paket).unwrap_err();
+            assert_eq!(
+                fehler.codec_art(),
+                Some(CodecFehlerArt::UngueltigesPaket),
+                "{paket:?}: {fehler}"
+            );
+            zaehler.fehler_erfassen(&fehler);
+        }
+
+        // 40-ms-Paket passt nicht in den 20-ms-Puffer
+        let mut lang = config.clone();
+        lang.frame_size = FrameSizeMs::Ms40;
+        let mut enc = OpusEncoder::new(lang).unwrap();
+        let paket = enc.encode(&vec![0.1; enc.frame_size()]).unwrap();
+        let fehler = dec.decode(&paket).unwrap_err();
+        assert_eq!(fehler.codec_art(), Some(CodecFehlerArt::PufferZuKlein));
+        zaehler.fehler_erfassen(&fehler);
+
+        // Der Decoder bleibt danach benutzbar
+        let mut enc = OpusEncoder::new(config).unwrap();
+        let paket = enc.encode(&vec![0.1; enc.frame_size()]).unwrap();
+        assert_eq!(dec.decode(&paket).unwrap().len(), 960);
+
+        zaehler.fehler_erfassen(&AudioError::RingBufferVoll);
+        let statistik = zaehler.statistik();
+        assert_eq!(statistik.ungueltiges_paket, 4);
+        assert_eq!(statistik.puffer_zu_klein, 1);
+        assert_eq!(statistik.opus_intern, 0);
+        assert_eq!(statistik.letzter_opus_code, None);
+    }
+
+    #[test]
+    fn interne_opus_fehler_behalten_den_code() {
+        let zaehler = CodecZaehler::default();
+        zaehler.erfassen(CodecFehlerArt::OpusIntern(-3));
+        zaehler.erfassen(CodecFehlerArt::OpusIntern(-7));
+        let statistik = zaehler.statistik();
+        assert_eq!(statistik.opus_intern, 2);
+        assert_eq!(statistik.letzter_opus_code, Some(-7));
+        assert_eq!(
+            CodecFehlerArt::OpusIntern(-3).to_string(),
+            "interner Opus-Fehler -3"
+        );
+    }
+
+    #[test]
+    fn frames_werden_vor_dem_encoder_angeglichen() {
+        let zaehler = CodecZaehler::default();
+
+        let mut passend = vec![0.5; 960];
+        frame_angleichen(&mut passend, 960, &zaehler).unwrap();
+
+        // Zu kurz: mit Stille auf die ausgehandelte Groesse
+        let mut kurz = vec![0.5; 900];
+        frame_angleichen(&mut kurz, 960, &zaehler).unwrap();
+        assert_eq!(kurz.len(), 960);
+        assert_eq!(kurz[899], 0.5);
+        assert_eq!(kurz[900], 0.0);
+
+        // Zu lang: abgelehnt, der Frame bleibt unveraendert
+        let mut lang = vec![0.5; 1920];
+        let fehler = frame_angleichen(&mut lang, 960, &zaehler).unwrap_err();
+        assert_eq!(
+            fehler.codec_art(),
+            Some(CodecFehlerArt::UngueltigeFrameGroesse)
+        );
+        assert_eq!(lang.len(), 1920);
+
+        let statistik = zaehler.statistik();
+        assert_eq!(statistik.frames_aufgefuellt, 1);
+        assert_eq!(statistik.ungueltige_frame_groesse, 1);
     }
 
     #[test]
@@ -320,4 +917,99 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn mulaw_grenzwerte() {
+        assert_eq!(mulaw_kodieren(0), 0xFF);
+        assert_eq!(mulaw_dekodieren(0xFF), 0);
+        assert_eq!(mulaw_kodieren(i16::MAX), 0x80);
+        assert_eq!(mulaw_kodieren(i16::MIN), 0x00);
+        assert_eq!(mulaw_dekodieren(0x80), 32124);
+        assert_eq!(mulaw_dekodieren(0x00), -32124);
+    }
+
+    #[test]
+    fn pcmu_roundtrip() {
+        let mut enc = PcmuEncoder::new();
+        let mut dec = PcmuDecoder::new();
+        let pcm_in: Vec<f32> = (0..enc.frame_size())
+            .map(|i| (i as f32 / 48000.0 * 440.0 * std::f32::consts::TAU).sin() * 0.5)
+            .collect();
+
+        let encoded = enc.encode(&pcm_in).unwrap();
+        assert_eq!(encoded.len(), 160);
+        let decoded = dec.decode(&encoded).unwrap();
+        assert_eq!(decoded.len(), pcm_in.len());
+        // Verlustbehaftet, aber nah am Original (um eine halbe Gruppe verzoegert)
+        let abweichung = pcm_in[..pcm_in.len() - 3]
+            .iter()
+            .zip(&decoded[3..])
+            .map(|(a, b)| (a - b).abs())
+            .fold(0.0f32, f32::max);
+        assert!(abweichung < 0.06, "Abweichung {abweichung}");
+    }
+
+    #[test]
+    fn sprach_decoder_prueft_opus_config() {
+        let mut config = AudioPreset::Speech.config();
+        config.complexity = 11; // Ungueltig
+        assert!(SprachDecoder::new(AudioCodec::Opus, &config).is_err());
+        // PCMU braucht keine Opus-Konfiguration
+        assert!(SprachDecoder::new(AudioCodec::Pcmu, &config).is_ok());
+        let enc = SprachEncoder::new(AudioCodec::Pcmu, config).unwrap();
+        assert_eq!(enc.codec(), AudioCodec::Pcmu);
+    }
+
+    #[test]
+    fn bitrate_im_laufenden_betrieb_umstellen() {
+        let mut enc = OpusEncoder::new(AudioPreset::Balanced.config()).unwrap();
+        let frame = vec![0.1f32; enc.frame_size()];
+        enc.encode(&frame).unwrap();
+
+        enc.set_bitrate(24).unwrap();
+        assert_eq!(enc.config().bitrate_kbps, 24);
+        assert!(enc.encode(&frame).is_ok());
+
+        // Ungueltige Bitrate aendert nichts
+        assert!(enc.set_bitrate(5).is_err());
+        assert_eq!(enc.config().bitrate_kbps, 24);
+    }
+
+    #[test]
+    fn vorgabe_erreicht_encoder_vor_dem_naechsten_frame() {
+        let ziel = Arc::new(AtomicU32::new(0));
+        let mut vorgabe = BitrateVorgabe::neu(Arc::clone(&ziel), AudioPreset::Balanced);
+        let mut enc = SprachEncoder::new(AudioCodec::Opus, AudioPreset::Balanced.config()).unwrap();
+
+        // Ohne Vorgabe bleibt die Preset-Bitrate
+        assert_eq!(vorgabe.anwenden(&mut enc).unwrap(), None);
+        assert_eq!(enc.bitrate_kbps(), Some(64));
+
+        // Kritische Lage: der Server senkt 64 -> 36 kbps
+        ziel.store(36, Ordering::Relaxed);
+        assert_eq!(vorgabe.anwenden(&mut enc).unwrap(), Some(36));
+        assert_eq!(enc.bitrate_kbps(), Some(36));
+        // Unveraenderte Vorgabe wird nicht erneut gesetzt
+        assert_eq!(vorgabe.anwenden(&mut enc).unwrap(), None);
+    }
+
+    #[test]
+    fn vorgabe_wird_auf_preset_grenzen_begrenzt() {
+        let ziel = Arc::new(AtomicU32::new(8));
+        let mut vorgabe = BitrateVorgabe::neu(Arc::clone(&ziel), AudioPreset::Balanced);
+        let mut enc = SprachEncoder::new(AudioCodec::Opus, AudioPreset::Balanced.config()).unwrap();
+
+        assert_eq!(vorgabe.anwenden(&mut enc).unwrap(), Some(16));
+        ziel.store(510, Ordering::Relaxed);
+        assert_eq!(vorgabe.anwenden(&mut enc).unwrap(), Some(64));
+        ziel.store(100_000, Ordering::Relaxed);
+        assert_eq!(vorgabe.anwenden(&mut enc).unwrap(), None);
+        assert_eq!(enc.bitrate_kbps(), Some(64));
+
+        // PCMU hat eine feste Rate
+        let mut pcmu =
+            SprachEncoder::new(AudioCodec::Pcmu, AudioPreset::Balanced.config()).unwrap();
+        ziel.store(24, Ordering::Relaxed);
+        assert_eq!(vorgabe.anwenden(&mut pcmu).unwrap(), None);
+    }
 }
@@ -4,9 +4,90 @@
 //! nutzt einen einfachen Delay-basierten Ansatz mit Korrelationsschaetzung.
 //! Fuer produktionsreife AEC sollte eine externe Bibliothek (z.B. speex DSP)
 //! eingebunden werden.
+//!
+//! Die Referenz (was gerade abgespielt wird) kommt ueber [`echo_referenz`]:
+//! der Playback-Pfad speist jeden ausgegebenen Frame in [`EchoReferenz`] ein,
+//! der Canceller liest mit [`ReferenzQuelle`] je Mikrofon-Frame gleich viele
+//! Samples. Beide laufen im Echtzeit-Takt; den festen Versatz zwischen
+//! Ausgabe und Echo im Mikrofon (Geraetepuffer, Weg durch den Raum) liefert
+//! der [`VerzoegerungsSchaetzer`].
+
+use std::sync::Mutex;
+
+use ringbuf::traits::{Consumer, Observer, Producer, Split};
+use ringbuf::{HeapCons, HeapProd, HeapRb};
 
 use super::AudioProcessor;
 
+/// Rueckstand der Referenz gegenueber dem Mikrofon (Samples), ab dem die
+/// aeltesten Referenz-Samples verworfen werden (40ms bei 48kHz)
+pub const MAX_REFERENZ_RUECKSTAND: usize = 1920;
+
+/// Schreibseite der Echo-Referenz (Playback-Pfad)
+pub struct EchoReferenz {
+    producer: HeapProd<f32>,
+}
+
+impl EchoReferenz {
+    /// Reiht abgespielte Samples ein; ist der Puffer voll (Mikrofon
+    /// geschlossen), fallen sie weg. Liefert die Anzahl uebernommener Samples.
+    pub fn einspeisen(&mut self, samples: &[f32]) -> usize {
+        self.producer.push_slice(samples)
+    }
+}
+
+/// Leseseite der Echo-Referenz (Capture-Pfad)
+pub struct ReferenzQuelle {
+    /// Hinter einem Mutex, damit der Canceller `Sync` bleibt; gelesen wird
+    /// ueber `&mut self` ohne zu sperren
+    consumer: Mutex<HeapCons<f32>>,
+}
+
+impl ReferenzQuelle {
+    /// Fuellt `ziel` mit der Referenz zum aktuellen Mikrofon-Frame
+    ///
+    /// Ein Rueckstand ueber [`MAX_REFERENZ_RUECKSTAND`] wird vorher
+    /// verworfen; fehlende Referenz gilt als Stille.
+    pub fn lesen(&mut self, ziel: &mut [f32]) {
+        let consumer = self.consumer.get_mut().unwrap_or_else(|e| e.into_inner());
+        let ueberschuss = consumer
+            .occupied_len()
+            .saturating_sub(ziel.len() + MAX_REFERENZ_RUECKSTAND);
+        if ueberschuss > 0 {
+            consumer.skip(ueberschuss);
+        }
+        let gelesen = consumer.pop_slice(ziel);
+        ziel[gelesen..].fill(0.0);
+    }
+}
+
+/// Begrenzter Puffer fuer die Echo-Referenz (`kapazitaet` in Samples)
+pub fn echo_referenz(kapazitaet: usize) -> (EchoReferenz, ReferenzQuelle) {
+    let (producer, consumer) = HeapRb::<f32>::new(kapazitaet).split();
+    (
+        EchoReferenz { producer },
+        ReferenzQuelle {
+            consumer: Mutex::new(consumer),
+        },
+    )
+}
+
+/// Bestimmt den Versatz zwischen Referenz und Echo im Mikrofon
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VerzoegerungsSchaetzer {
+    /// Fest konfigurierter Versatz in Samples
+    Fest(usize),
+}
+
+impl VerzoegerungsSchaetzer {
+    /// Aktuelle Verzoegerung in Samples
+    pub fn verzoegerung(&self) -> usize {
+        match self {
+            Self::Fest(samples) => *samples,
+        }
+    }
+}
+
 /// Konfiguration fuer Echo Cancellation
 #[derive(Debug, Clone)]
 pub struct EchoCancelConfig {
@@ -16,6 +97,22 @@ pub struct EchoCancelConfig {
     pub cancellation_strength: f32,
     /// Adaptionsrate des Filters
     pub adaptation_rate: f32,
+    /// Versatz zwischen Referenz und Echo
+    pub verzoegerung: VerzoegerungsSchaetzer,
+}
+
+impl EchoCancelConfig {
+    /// Konfiguration aus Echo-Laenge und festem Versatz (beides in ms, 48kHz)
+    pub fn aus_ms(tail_length_ms: u32, versatz_ms: u32) -> Self {
+        let max_delay_samples = (tail_length_ms as usize * 48).max(1);
+        Self {
+            max_delay_samples,
+            verzoegerung: VerzoegerungsSchaetzer::Fest(
+                (versatz_ms as usize * 48).min(max_delay_samples - 1),
+            ),
+            ..Self::default()
+        }
+    }
 }
 
 impl Default for EchoCancelConfig {
@@ -24,6 +121,7 @@ impl Default for EchoCancelConfig {
             max_delay_samples: 4800, // 100ms bei 48kHz
             cancellation_strength: 0.7,
             adaptation_rate: 0.01,
+            verzoegerung: VerzoegerungsSchaetzer::Fest(1200), // 25ms
         }
     }
 }
@@ -31,7 +129,9 @@ impl Default for EchoCancelConfig {
 /// Vereinfachter Echo Canceller
 ///
 /// Speichert das Ausgabe-Signal (Referenz) und subtrahiert eine
-/// verzoegerte, skalierte Version davon vom Eingangssignal.
+/// verzoegerte, skalierte Version davon vom Eingangssignal. Der Faktor
+/// startet bei `cancellation_strength` und folgt mit `adaptation_rate`
+/// (normalisiertes LMS) der tatsaechlichen Echo-Daempfung.
 pub struct EchoCanceller {
     config: EchoCancelConfig,
     /// Ring-Buffer fuer das Referenzsignal (Lautsprecher-Output)
@@ -39,19 +139,38 @@ pub struct EchoCanceller {
     write_pos: usize,
     /// Geschaetzte Echo-Verzoegerung in Samples
     estimated_delay: usize,
+    /// Geschaetzte Echo-Daempfung
+    echo_gain: f32,
+    /// Geglaettete Leistung der Referenz (Normierung der Adaption)
+    referenz_leistung: f32,
+    /// Referenz aus dem Playback-Pfad (`None` = nur `feed_reference`)
+    referenz: Option<ReferenzQuelle>,
+    referenz_frame: Vec<f32>,
     enabled: bool,
 }
 
 impl EchoCanceller {
     pub fn new(config: EchoCancelConfig) -> Self {
         let buf_size = config.max_delay_samples;
-        Self {
+        let mut aec = Self {
             reference_buffer: vec![0.0; buf_size],
             write_pos: 0,
-            estimated_delay: config.max_delay_samples / 4, // Initiale Schaetzung: 25ms
+            estimated_delay: 0,
+            echo_gain: config.cancellation_strength,
+            referenz_leistung: 0.0,
+            referenz: None,
+            referenz_frame: Vec::new(),
             config,
             enabled: true,
-        }
+        };
+        aec.set_delay(aec.config.verzoegerung.verzoegerung());
+        aec
+    }
+
+    /// Liest die Referenz je Frame selbst aus dem Playback-Pfad
+    pub fn mit_referenz(mut self, referenz: ReferenzQuelle) -> Self {
+        self.referenz = Some(referenz);
+        self
     }
 
     /// Fuegt ein Referenz-Sample (Lautsprecher) in den Buffer ein.
@@ -63,9 +182,13 @@ impl EchoCanceller {
         }
     }
 
-    /// Liest ein verzoegertes Referenzsample
+    /// Liest ein verzoegertes Referenzsample (`offset` Samples vor dem
+    /// zuletzt eingespeisten; was aus dem Puffer gefallen ist, ist Stille)
     fn get_reference_sample(&self, offset: usize) -> f32 {
         let buf_len = self.reference_buffer.len();
+        if offset > buf_len {
+            return 0.0;
+        }
         let pos = (self.write_pos + buf_len - offset) % buf_len;
         self.reference_buffer[pos]
     }
@@ -82,17 +205,39 @@ impl AudioProcessor for EchoCanceller {
             return;
         }
 
+        // Referenz zum Frame holen, damit sie zeitgleich im Puffer liegt
+        if let Some(referenz) = self.referenz.as_mut() {
+            let mut frame = std::mem::take(&mut self.referenz_frame);
+            frame.resize(samples.len(), 0.0);
+            referenz.lesen(&mut frame);
+            self.feed_reference(&frame);
+            self.referenz_frame = frame;
+        }
+
+        let n = samples.len();
         for (i, sample) in samples.iter_mut().enumerate() {
-            // Echo-Schaetzung: verzoegertes Referenzsignal
-            let echo_estimate = self.get_reference_sample(self.estimated_delay + i);
+            // Echo-Schaetzung: verzoegertes Referenzsignal (Sample i liegt
+            // n - i Samples vor dem Ende der zuletzt eingespeisten Referenz)
+            let echo_estimate = self.get_reference_sample(self.estimated_delay + n - i);
             // Echo subtrahieren
-            *sample -= echo_estimate * self.config.cancellation_strength;
+            let fehler = *sample - echo_estimate * self.echo_gain;
+            // Daempfung nachfuehren (NLMS mit einem Koeffizienten)
+            self.referenz_leistung =
+                0.99 * self.referenz_leistung + 0.01 * echo_estimate * echo_estimate;
+            if self.referenz_leistung > 1e-6 {
+                self.echo_gain +=
+                    self.config.adaptation_rate * fehler * echo_estimate / self.referenz_leistung;
+                self.echo_gain = self.echo_gain.clamp(0.0, 2.0);
+            }
+            *sample = fehler;
         }
     }
 
     fn reset(&mut self) {
         self.reference_buffer.fill(0.0);
         self.write_pos = 0;
+        self.echo_gain = self.config.cancellation_strength;
+        self.referenz_leistung = 0.0;
     }
 
     fn is_enabled(&self) -> bool {
@@ -149,6 +294,7 @@ mod tests {
             max_delay_samples: 480,
             cancellation_strength: 1.0,
             adaptation_rate: 0.01,
+            ..EchoCancelConfig::default()
         });
         // Referenz einspeisung
         let reference = vec![0.5f32; 480];
@@ -169,4 +315,70 @@ mod tests {
         aec.set_delay(999999); // Weit ueber max
         assert!(aec.estimated_delay < aec.config.max_delay_samples);
     }
+
+    #[test]
+    fn referenz_rueckstand_wird_verworfen() {
+        let (mut playback, mut quelle) = echo_referenz(9600);
+        let mut frame = vec![1.0f32; 960];
+        quelle.lesen(&mut frame);
+        assert!(frame.iter().all(|&s| s == 0.0), "ohne Referenz: Stille");
+
+        let samples: Vec<f32> = (0..9600).map(|i| i as f32).collect();
+        assert_eq!(playback.einspeisen(&samples), 9600);
+        quelle.lesen(&mut frame);
+        assert_eq!(frame[0], (9600 - 960 - MAX_REFERENZ_RUECKSTAND) as f32);
+    }
+
+    /// Rauschartige Referenz (deterministisch)
+    fn referenz_signal(n: usize) -> Vec<f32> {
+        let mut x: u32 = 0x1234_5678;
+        (0..n)
+            .map(|_| {
+                x ^= x << 13;
+                x ^= x >> 17;
+                x ^= x << 5;
+                (x as f32 / u32::MAX as f32 - 0.5) * 0.6
+            })
+            .collect()
+    }
+
+    #[test]
+    fn referenz_aus_dem_playback_daempft_das_echo() {
+        const FRAME: usize = 960;
+        const FRAMES: usize = 50;
+        const VERSATZ: usize = 1440; // 30ms
+        let laenge = FRAME * FRAMES;
+        let referenz = referenz_signal(laenge);
+        let sprache: Vec<f32> = (0..laenge)
+            .map(|i| (i as f32 * 300.0 * std::f32::consts::TAU / 48000.0).sin() * 0.1)
+            .collect();
+        // Mikrofon hoert die Ausgabe 30ms spaeter und leiser
+        let echo: Vec<f32> = (0..laenge)
+            .map(|i| i.checked_sub(VERSATZ).map_or(0.0, |j| referenz[j] * 0.6))
+            .collect();
+
+        let (mut playback, quelle) = echo_referenz(9600);
+        let mut aec = EchoCanceller::new(EchoCancelConfig::aus_ms(100, 30)).mit_referenz(quelle);
+
+        let (mut echo_energie, mut rest_energie) = (0.0f32, 0.0f32);
+        for k in 0..FRAMES {
+            let bereich = k * FRAME..(k + 1) * FRAME;
+            playback.einspeisen(&referenz[bereich.clone()]);
+            let mut mic: Vec<f32> = bereich.clone().map(|i| echo[i] + sprache[i]).collect();
+            aec.process(&mut mic);
+            // Eingeschwungen: nur die zweite Haelfte auswerten
+            if k >= FRAMES / 2 {
+                for (j, i) in bereich.enumerate() {
+                    echo_energie += echo[i] * echo[i];
+                    let rest = mic[j] - sprache[i];
+                    rest_energie += rest * rest;
+                }
+            }
+        }
+        // Mindestens 10 dB weniger Echo, die Sprache bleibt
+        assert!(
+            rest_energie < echo_energie * 0.1,
+            "Echo nicht gedaempft: {rest_energie} von {echo_energie}"
+        );
+    }
 }
@@ -0,0 +1,362 @@
+//! Empfangsseite eines Voice-Streams
+//!
+//! [`EmpfangsStrom`] verfolgt die Sequenznummern eines Sprechers und gleicht
+//! einzelne verlorene Pakete aus, bevor das aktuelle dekodiert wird:
+//!
+//! - traegt das naechste Paket `VoiceFlags::FEC`, wird der verlorene Frame
+//!   aus dessen In-Band-FEC-Daten rekonstruiert (Opus)
+//! - sonst verdeckt PLC die Luecke
+//!
+//! Groessere Luecken werden nicht aufgefuellt – mehrere geschaetzte Frames
+//! klingen schlechter als eine kurze Pause. Verspaetete und doppelte Pakete
+//! werden verworfen, ihr Frame ist bereits ausgegeben oder verdeckt.
+//!
+//! Scheitern [`DECODER_RESET_NACH`] Pakete in Folge, gilt der Decoder-Zustand
+//! als gestoert und wird neu aufgesetzt.
+
+use speakeasy_protocol::voice::{VoiceFlags, VoicePacketHeader};
+
+use crate::codec::{CodecZaehler, SprachDecoder};
+use crate::error::{AudioError, AudioResult};
+
+/// Dekodierfehler in Folge, nach denen der Decoder zurueckgesetzt wird
+pub const DECODER_RESET_NACH: u32 = 5;
+
+/// Zaehler fuer ausgeglichene und verworfene Pakete
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct EmpfangsStatistik {
+    /// Aus FEC-Daten rekonstruierte Frames
+    pub fec_rekonstruiert: u64,
+    /// Per PLC verdeckte Frames (Einzelverlust ohne FEC, Dekodierfehler)
+    pub plc_verdeckt: u64,
+    /// Luecken ueber mehr als ein Paket
+    pub grosse_luecken: u64,
+    /// Verspaetete oder doppelte Pakete
+    pub verworfen: u64,
+    /// Nach [`DECODER_RESET_NACH`] Fehlern in Folge neu aufgesetzte Decoder
+    pub decoder_resets: u64,
+}
+
+/// Decoder eines eingehenden Streams mit Verlust-Ausgleich
+pub struct EmpfangsStrom {
+    decoder: SprachDecoder,
+    letzte_sequenz: Option<u32>,
+    statistik: EmpfangsStatistik,
+    /// Geteilte Fehlerzaehler aller Streams
+    zaehler: CodecZaehler,
+    fehler_in_folge: u32,
+}
+
+impl EmpfangsStrom {
+    pub fn neu(decoder: SprachDecoder) -> Self {
+        Self {
+            decoder,
+            letzte_sequenz: None,
+            statistik: EmpfangsStatistik::default(),
+            zaehler: CodecZaehler::default(),
+            fehler_in_folge: 0,
+        }
+    }
+
+    /// Erfasst Codec-Fehler in `zaehler` statt in eigenen Zaehlern
+    pub fn mit_zaehler(mut self, zaehler: CodecZaehler) -> Self {
+        self.zaehler = zaehler;
+        self
+    }
+
+    pub fn statistik(&self) -> EmpfangsStatistik {
+        self.statistik
+    }
+
+    /// Silence-Paket: nur die Sequenz merken, damit der naechste Sprachframe
+    /// nicht als Verlust gilt
+    pub fn stille(&mut self, sequenz: u32) {
+        if self.ist_neuer(sequenz) {
+            self.letzte_sequenz = Some(sequenz);
+        }
+    }
+
+    /// Paket bewusst nicht dekodiert (Warteschlange des Mischers voll): die
+    /// Sequenz fortschreiben, damit es weder als Verlust zaehlt noch per FEC
+    /// nachgeholt wird
+    pub fn ueberspringen(&mut self, sequenz: u32) {
+        self.stille(sequenz);
+    }
+
+    /// Dekodiert ein Audio-Paket samt Ausgleich eines Einzelverlusts davor
+    ///
+    /// Liefert den rekonstruierten und den aktuellen Frame hintereinander,
+    /// bei verworfenen Paketen nichts. Schlaegt das Dekodieren des aktuellen
+    /// Frames fehl, wird er per PLC ersetzt.
+    pub fn dekodieren(
+        &mut self,
+        header: &VoicePacketHeader,
+        payload: &[u8],
+    ) -> AudioResult<Vec<f32>> {
+        if !self.ist_neuer(header.sequence) {
+            self.statistik.verworfen += 1;
+            return Ok(Vec::new());
+        }
+
+        let verloren = self
+            .letzte_sequenz
+            .map_or(0, |letzte| header.sequence.wrapping_sub(letzte) - 1);
+        self.letzte_sequenz = Some(header.sequence);
+
+        let mut pcm = match verloren {
+            0 => Vec::new(),
+            1 if header.hat_flag(VoiceFlags::FEC) => match self.decoder.decode_fec(payload) {
+                Ok(frame) => {
+                    self.statistik.fec_rekonstruiert += 1;
+                    frame
+                }
+                Err(e) => {
+                    tracing::trace!("FEC-Rekonstruktion fehlgeschlagen: {}", e);
+                    self.zaehler.fehler_erfassen(&e);
+                    self.plc()?
+                }
+            },
+            1 => self.plc()?,
+            _ => {
+                self.statistik.grosse_luecken += 1;
+                Vec::new()
+            }
+        };
+
+        match self.decoder.decode(payload) {
+            Ok(frame) => {
+                self.fehler_in_folge = 0;
+                pcm.extend(frame);
+            }
+            Err(e) => {
+                tracing::trace!("Decoding fehlgeschlagen: {}", e);
+                self.dekodierfehler(&e)?;
+                let frame = self.plc()?;
+                pcm.extend(frame);
+            }
+        }
+        Ok(pcm)
+    }
+
+    /// Zum Abspielzeitpunkt fehlender Frame (Jitter-Puffer wartet noch):
+    /// per PLC verdecken, ohne die Sequenz weiterzuzaehlen; kommt das Paket
+    /// doch noch, wird es normal dekodiert. Vor dem ersten Paket liefert das
+    /// nichts.
+    pub fn verdecken(&mut self) -> AudioResult<Vec<f32>> {
+        if self.letzte_sequenz.is_none() {
+            return Ok(Vec::new());
+        }
+        self.plc()
+    }
+
+    fn plc(&mut self) -> AudioResult<Vec<f32>> {
+        self.statistik.plc_verdeckt += 1;
+        self.decoder
+            .decode_plc()
+            .inspect_err(|e| self.zaehler.fehler_erfassen(e))
+    }
+
+    /// Zaehlt einen Dekodierfehler und setzt den Decoder nach zu vielen in
+    /// Folge zurueck
+    fn dekodierfehler(&mut self, e: &AudioError) -> AudioResult<()> {
+        self.zaehler.fehler_erfassen(e);
+        self.fehler_in_folge += 1;
+        if self.fehler_in_folge < DECODER_RESET_NACH {
+            return Ok(());
+        }
+        tracing::debug!(
+            "{} Dekodierfehler in Folge (zuletzt: {}), Decoder wird zurueckgesetzt",
+            self.fehler_in_folge,
+            e
+        );
+        self.fehler_in_folge = 0;
+        self.statistik.decoder_resets += 1;
+        self.zaehler.decoder_reset();
+        self.decoder.zuruecksetzen()
+    }
+
+    /// Liegt `sequenz` hinter der zuletzt gesehenen (mit Ueberlauf)?
+    fn ist_neuer(&self, sequenz: u32) -> bool {
+        self.letzte_sequenz.is_none_or(|letzte| {
+            let abstand = sequenz.wrapping_sub(letzte);
+            abstand != 0 && abstand < u32::MAX / 2
+        })
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::codec::SprachEncoder;
+    use speakeasy_protocol::codec::AudioPreset;
+    use speakeasy_protocol::voice::{AudioCodec, PacketType};
+
+    /// 440-Hz-Sinus bei 48 kHz, Frame `n`
+    fn ton_frame(n: usize) -> Vec<f32> {
+        (n * 960..(n + 1) * 960)
+            .map(|i| (i as f32 * 440.0 * std::f32::consts::TAU / 48000.0).sin() * 0.5)
+            .collect()
+    }
+
+    /// Kodierte Pakete; jedes 20. geht verloren (5 % Einzelverluste)
+    fn pakete_mit_verlust(codec: AudioCodec, anzahl: u32) -> Vec<(VoicePacketHeader, Vec<u8>)> {
+        let config = AudioPreset::Balanced.config();
+        let mut encoder = SprachEncoder::new(codec, config).unwrap();
+        let flags = if encoder.fec_aktiv() {
+            VoiceFlags::FEC
+        } else if codec == AudioCodec::Pcmu {
+            VoiceFlags::PCMU
+        } else {
+            0
+        };
+        (0..anzahl)
+            .map(|seq| {
+                let payload = encoder.encode(&ton_frame(seq as usize)).unwrap();
+                let header = VoicePacketHeader::new(PacketType::Audio, flags, seq, seq * 960, 7);
+                (header, payload)
+            })
+            .filter(|(header, _)| header.sequence % 20 != 10)
+            .collect()
+    }
+
+    fn decoder(codec: AudioCodec) -> SprachDecoder {
+        SprachDecoder::new(codec, &AudioPreset::Balanced.config()).unwrap()
+    }
+
+    #[test]
+    fn fec_fuellt_einzelverluste() {
+        let pakete = pakete_mit_verlust(AudioCodec::Opus, 100);
+        assert_eq!(pakete.len(), 95);
+        assert!(pakete[0].0.hat_flag(VoiceFlags::FEC));
+
+        // Bisher: nur empfangene Pakete dekodieren
+        let mut einfach = decoder(AudioCodec::Opus);
+        let ohne_ausgleich: usize = pakete
+            .iter()
+            .map(|(_, payload)| einfach.decode(payload).unwrap().len())
+            .sum();
+
+        let mut strom = EmpfangsStrom::neu(decoder(AudioCodec::Opus));
+        let mit_fec: usize = pakete
+            .iter()
+            .map(|(header, payload)| strom.dekodieren(header, payload).unwrap().len())
+            .sum();
+
+        assert_eq!(ohne_ausgleich, 95 * 960);
+        assert_eq!(mit_fec, 100 * 960, "keine hoerbaren Luecken mehr");
+        assert_eq!(strom.statistik().fec_rekonstruiert, 5);
+        assert_eq!(strom.statistik().plc_verdeckt, 0);
+    }
+
+    #[test]
+    fn ohne_fec_verdeckt_plc() {
+        let pakete = pakete_mit_verlust(AudioCodec::Pcmu, 40);
+        let mut strom = EmpfangsStrom::neu(decoder(AudioCodec::Pcmu));
+        let samples: usize = pakete
+            .iter()
+            .map(|(header, payload)| strom.dekodieren(header, payload).unwrap().len())
+            .sum();
+
+        assert_eq!(samples, 40 * 960);
+        assert_eq!(strom.statistik().plc_verdeckt, 2);
+        assert_eq!(strom.statistik().fec_rekonstruiert, 0);
+    }
+
+    #[test]
+    fn stille_grosse_luecken_und_verspaetete_pakete() {
+        let pakete = pakete_mit_verlust(AudioCodec::Pcmu, 8);
+        let mut strom = EmpfangsStrom::neu(decoder(AudioCodec::Pcmu));
+        let dekodieren = |strom: &mut EmpfangsStrom, i: usize| {
+            let (header, payload) = &pakete[i];
+            strom.dekodieren(header, payload).unwrap().len()
+        };
+
+        assert_eq!(dekodieren(&mut strom, 0), 960);
+        // Sequenz 1 war ein Silence-Paket: kein Verlust
+        strom.stille(1);
+        assert_eq!(dekodieren(&mut strom, 2), 960);
+        // Sequenz 3 und 4 fehlen: nicht auffuellen
+        assert_eq!(dekodieren(&mut strom, 5), 960);
+        // Verspaetetes und doppeltes Paket
+        assert_eq!(dekodieren(&mut strom, 3), 0);
+        assert_eq!(dekodieren(&mut strom, 5), 0);
+
+        let statistik = strom.statistik();
+        assert_eq!(statistik.grosse_luecken, 1);
+        assert_eq!(statistik.verworfen, 2);
+        assert_eq!(statistik.plc_verdeckt, 0);
+    }
+
+    #[test]
+    fn verdecken_haelt_die_sequenz() {
+        let pakete = pakete_mit_verlust(AudioCodec::Pcmu, 4);
+        let mut strom = EmpfangsStrom::neu(decoder(AudioCodec::Pcmu));
+        assert!(strom.verdecken().unwrap().is_empty());
+
+        let (header, payload) = &pakete[0];
+        assert_eq!(strom.dekodieren(header, payload).unwrap().len(), 960);
+        // Sequenz 1 ist zum Abspielzeitpunkt noch nicht da
+        assert_eq!(strom.verdecken().unwrap().len(), 960);
+        // Kommt sie doch noch, wird sie ohne weiteren Ausgleich dekodiert
+        let (header, payload) = &pakete[1];
+        assert_eq!(strom.dekodieren(header, payload).unwrap().len(), 960);
+
+        let statistik = strom.statistik();
+        assert_eq!(statistik.plc_verdeckt, 1);
+        assert_eq!(statistik.verworfen, 0);
+    }
+
+    #[test]
+    fn decoder_wird_nach_fehlern_in_folge_zurueckgesetzt() {
+        let zaehler = CodecZaehler::default();
+        let mut strom = EmpfangsStrom::neu(decoder(AudioCodec::Opus)).mit_zaehler(zaehler.clone());
+        let header = |seq| VoicePacketHeader::new(PacketType::Audio, 0, seq, seq * 960, 7);
+        let kaputt = [0xFF];
+
+        // Ein gutes Paket unterbricht die Fehlerfolge
+        let pakete = pakete_mit_verlust(AudioCodec::Opus, 1);
+        for seq in 0..DECODER_RESET_NACH - 1 {
+            assert_eq!(strom.dekodieren(&header(seq), &kaputt).unwrap().len(), 960);
+        }
+        assert_eq!(
+            strom
+                .dekodieren(&header(DECODER_RESET_NACH - 1), &pakete[0].1)
+                .unwrap()
+                .len(),
+            960
+        );
+        assert_eq!(strom.statistik().decoder_resets, 0);
+
+        // Erst die volle Folge setzt zurueck, danach beginnt sie neu
+        for seq in DECODER_RESET_NACH..DECODER_RESET_NACH * 3 {
+            assert_eq!(strom.dekodieren(&header(seq), &kaputt).unwrap().len(), 960);
+        }
+        assert_eq!(strom.statistik().decoder_resets, 2);
+        assert_eq!(
+            strom.statistik().plc_verdeckt,
+            3 * DECODER_RESET_NACH as u64 - 1
+        );
+
+        let statistik = zaehler.statistik();
+        assert_eq!(
+            statistik.ungueltiges_paket,
+            3 * DECODER_RESET_NACH as u64 - 1
+        );
+        assert_eq!(statistik.decoder_resets, 2);
+    }
+
+    #[test]
+    fn sequenz_ueberlauf_ist_kein_verlust() {
+        let mut strom = EmpfangsStrom::neu(decoder(AudioCodec::Pcmu));
+        let payload = [0xFF; 160];
+        for seq in [u32::MAX - 1, u32::MAX, 0, 1] {
+            let header = VoicePacketHeader::new(PacketType::Audio, VoiceFlags::PCMU, seq, 0, 7);
+            assert_eq!(strom.dekodieren(&header, &payload).unwrap().len(), 960);
+        }
+        assert_eq!(strom.statistik(), EmpfangsStatistik::default());
+    }
+}
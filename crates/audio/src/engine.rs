@@ -9,6 +9,7 @@ use std::sync::Arc;
 use tracing::{debug, error, info};
 
 use crate::capture::CaptureConfig;
+use crate::codec::{CodecStatistik, CodecZaehler};
 use crate::error::{AudioError, AudioResult};
 use crate::pipeline::{build_default_capture_pipeline, build_minimal_capture_pipeline};
 use crate::playback::PlaybackConfig;
@@ -27,6 +28,12 @@ pub struct AudioStats {
     pub output_level: f32,
     /// Ob aktuell gesendet wird
     pub is_transmitting: bool,
+    /// Playback-Callbacks mit zu wenig Samples (Unterlaeufe) seit Start
+    pub playback_underruns: u64,
+    /// Aktuelles Vorpuffer-Ziel des Playbacks in Samples
+    pub playback_vorpuffer: usize,
+    /// Codec-Fehler je Kategorie seit Start
+    pub codec: CodecStatistik,
 }
 
 /// Konfiguration der Audio-Engine
@@ -44,6 +51,8 @@ pub struct AudioEngineConfig {
     pub ptt_mode: PttMode,
     /// Minimale Pipeline (ohne Echo-Cancellation und De-Esser)
     pub minimal_pipeline: bool,
+    /// Gemeinsame Codec-Fehlerzaehler fuer Sende- und Empfangsweg
+    pub codec_zaehler: CodecZaehler,
 }
 
 impl Default for AudioEngineConfig {
@@ -55,6 +64,7 @@ impl Default for AudioEngineConfig {
             playback: PlaybackConfig::default(),
             ptt_mode: PttMode::VoiceActivation,
             minimal_pipeline: false,
+            codec_zaehler: CodecZaehler::default(),
         }
     }
 }
@@ -178,7 +188,11 @@ impl AudioEngine {
 
     /// Gibt aktuelle Statistiken zurueck
     pub fn get_audio_stats(&self) -> AudioStats {
-        self.state.read().stats.clone()
+        let mut stats = self.state.read().stats.clone();
+        stats.playback_underruns = self.config.playback.unterlauf_zaehler.unterlaeufe();
+        stats.playback_vorpuffer = self.config.playback.budget.vorpuffer_ziel();
+        stats.codec = self.config.codec_zaehler.statistik();
+        stats
     }
 
     /// Gibt zurueck ob Capture aktiv ist
@@ -220,7 +234,7 @@ fn audio_engine_thread(
     let mut _pipeline = if config.minimal_pipeline {
         build_minimal_capture_pipeline()
     } else {
-        build_default_capture_pipeline()
+        build_default_capture_pipeline(None)
     };
 
     debug!("Audio-Engine Thread gestartet");
@@ -297,6 +311,7 @@ mod tests {
         let engine = AudioEngine::new(AudioEngineConfig::default()).unwrap();
         let stats = engine.get_audio_stats();
         assert_eq!(stats.frames_processed, 0);
+        assert_eq!(stats.playback_underruns, 0);
         assert!(!stats.is_transmitting);
     }
 
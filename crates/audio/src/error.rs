@@ -1,7 +1,10 @@
 //! Fehlertypen fuer die Audio-Engine
 
+use speakeasy_core::SpeakeasyError;
 use thiserror::Error;
 
+use crate::codec::CodecFehlerArt;
+
 /// Alle moeglichen Fehler der Audio-Engine
 #[derive(Debug, Error)]
 pub enum AudioError {
@@ -17,8 +20,8 @@ pub enum AudioError {
     #[error("Stream-Fehler: {0}")]
     StreamFehler(String),
 
-    #[error("Codec-Fehler: {0}")]
-    CodecFehler(String),
+    #[error("Codec-Fehler ({art}): {grund}")]
+    CodecFehler { art: CodecFehlerArt, grund: String },
 
     #[error("Konfigurationsfehler: {0}")]
     Konfiguration(String),
@@ -43,3 +46,68 @@ pub enum AudioError {
 }
 
 pub type AudioResult<T> = Result<T, AudioError>;
+
+impl AudioError {
+    /// Kategorie eines Codec-Fehlers (`None` fuer alle anderen Fehler)
+    pub fn codec_art(&self) -> Option<CodecFehlerArt> {
+        match self {
+            AudioError::CodecFehler { art, .. } => Some(*art),
+            _ => None,
+        }
+    }
+}
+
+impl From<AudioError> for SpeakeasyError {
+    fn from(e: AudioError) -> Self {
+        match e {
+            AudioError::CodecFehler { .. } => Self::Codec(e.to_string()),
+            AudioError::Konfiguration(grund) => Self::Konfiguration(grund),
+            AudioError::Io(io) => io.into(),
+            AudioError::Anyhow(e) => Self::Anyhow(e),
+            AudioError::GeraetNichtGefunden(_)
+            | AudioError::KeinStandardEingabegeraet
+            | AudioError::KeinStandardAusgabegeraet
+            | AudioError::StreamFehler(_)
+            | AudioError::PipelineNichtInitialisiert
+            | AudioError::KalibrierungsTimeout
+            | AudioError::RingBufferVoll
+            | AudioError::RingBufferLeer => Self::Audio(e.to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use speakeasy_core::FehlerCode;
+
+    #[test]
+    fn jede_variante_hat_einen_fachlichen_code() {
+        let varianten = vec![
+            AudioError::GeraetNichtGefunden("x".into()),
+            AudioError::KeinStandardEingabegeraet,
+            AudioError::KeinStandardAusgabegeraet,
+            AudioError::StreamFehler("x".into()),
+            AudioError::CodecFehler {
+                art: CodecFehlerArt::UngueltigesPaket,
+                grund: "x".into(),
+            },
+            AudioError::Konfiguration("x".into()),
+            AudioError::PipelineNichtInitialisiert,
+            AudioError::KalibrierungsTimeout,
+            AudioError::RingBufferVoll,
+            AudioError::RingBufferLeer,
+            AudioError::Io(std::io::Error::new(std::io::ErrorKind::NotFound, "x")),
+        ];
+        for e in varianten {
+            let anzeige = e.to_string();
+            assert_ne!(
+                SpeakeasyError::from(e).code(),
+                FehlerCode::Intern,
+                "{anzeige}"
+            );
+        }
+        let e = AudioError::Anyhow(anyhow::anyhow!("x"));
+        assert_eq!(SpeakeasyError::from(e).code(), FehlerCode::Intern);
+    }
+}
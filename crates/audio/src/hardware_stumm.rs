@@ -0,0 +1,296 @@
+//! Hardware-Stummschaltung – Mute-Taste am Headset bzw. im Betriebssystem
+//!
+//! Viele Headsets schalten das Mikrofon ueber eine eigene Taste auf
+//! Betriebssystem-Ebene stumm; der Client liefert dann weiter Frames, die
+//! nur noch Stille enthalten. Erkannt wird das auf zwei Wegen:
+//!
+//! - Unter Windows fragt [`system_stumm_abfragen`] den Mute-Zustand des
+//!   Standard-Aufnahmegeraets per WASAPI (`IAudioEndpointVolume`) ab.
+//! - Ueberall sonst (und als Ergaenzung) erkennt [`NullSignalDetektor`] ein
+//!   anhaltendes, exakt digitales Null-Signal.
+//!
+//! Ein echtes Mikrofon in einem stillen Raum liefert immer etwas Rauschen,
+//! also Samples ungleich `0.0`. Nur exakte Nullen zaehlen deshalb als
+//! Hinweis auf eine Hardware-Stummschaltung, ein beliebig leises Signal nie.
+
+use std::time::Duration;
+
+/// Standard-Dauer eines Null-Signals bis zur Meldung
+pub const STANDARD_NULL_DAUER: Duration = Duration::from_secs(5);
+
+/// Kuerzeste erlaubte Dauer; darunter wuerden Aussetzer des Treibers melden
+pub const MIN_NULL_DAUER: Duration = Duration::from_millis(500);
+
+/// Laengste erlaubte Dauer
+pub const MAX_NULL_DAUER: Duration = Duration::from_secs(60);
+
+/// Erkennt ein anhaltendes, exakt digitales Null-Signal
+///
+/// Zaehlt die Null-Samples seit dem letzten Sample ungleich `0.0`. Erreicht
+/// die Zahl die konfigurierte Dauer, gilt das Mikrofon als stumm; ein
+/// einziges Sample ungleich `0.0` hebt das sofort wieder auf.
+#[derive(Debug, Clone)]
+pub struct NullSignalDetektor {
+    /// Anzahl Null-Samples bis zur Meldung
+    schwelle: u64,
+    /// Null-Samples seit dem letzten Sample ungleich 0.0
+    null_samples: u64,
+    stumm: bool,
+}
+
+impl NullSignalDetektor {
+    /// Erstellt einen Detektor fuer `sample_rate` Hz (Mono)
+    ///
+    /// `dauer` wird auf [`MIN_NULL_DAUER`]..=[`MAX_NULL_DAUER`] begrenzt.
+    pub fn neu(sample_rate: u32, dauer: Duration) -> Self {
+        let dauer = dauer.clamp(MIN_NULL_DAUER, MAX_NULL_DAUER);
+        let schwelle = (sample_rate as u128 * dauer.as_millis() / 1000).max(1) as u64;
+        Self {
+            schwelle,
+            null_samples: 0,
+            stumm: false,
+        }
+    }
+
+    /// Verarbeitet einen Block Samples
+    ///
+    /// Gibt den neuen Zustand zurueck, wenn er sich geaendert hat.
+    pub fn verarbeiten(&mut self, samples: &[f32]) -> Option<bool> {
+        match samples.iter().rposition(|&s| s != 0.0) {
+            Some(letztes) => self.null_samples = (samples.len() - letztes - 1) as u64,
+            None => self.null_samples = self.null_samples.saturating_add(samples.len() as u64),
+        }
+        let stumm = self.null_samples >= self.schwelle;
+        if stumm == self.stumm {
+            return None;
+        }
+        self.stumm = stumm;
+        Some(stumm)
+    }
+
+    /// Gilt das Mikrofon gerade als stumm?
+    pub fn ist_stumm(&self) -> bool {
+        self.stumm
+    }
+
+    /// Vergisst den bisherigen Verlauf (z.B. nach Geraetewechsel)
+    pub fn zuruecksetzen(&mut self) {
+        self.null_samples = 0;
+        self.stumm = false;
+    }
+}
+
+/// Abfrage des Mute-Zustands beim Betriebssystem (`None` = nicht verfuegbar)
+pub type SystemAbfrage = fn() -> Option<bool>;
+
+/// Kombinierte Erkennung aus Betriebssystem-Zustand und Null-Signal
+///
+/// Das Betriebssystem wird hoechstens einmal pro Sekunde Audio befragt; der
+/// Takt ergibt sich aus den verarbeiteten Samples, nicht aus der Uhr.
+#[derive(Debug, Clone)]
+pub struct HardwareStummErkennung {
+    null: NullSignalDetektor,
+    abfrage: SystemAbfrage,
+    /// Samples zwischen zwei Abfragen beim Betriebssystem
+    abfrage_intervall: u64,
+    /// Samples seit der letzten Abfrage (`None` = noch nie abgefragt)
+    seit_abfrage: Option<u64>,
+    system: Option<bool>,
+    stumm: bool,
+}
+
+impl HardwareStummErkennung {
+    /// Erstellt die Erkennung mit der Abfrage des aktuellen Systems
+    pub fn neu(sample_rate: u32, null_dauer: Duration) -> Self {
+        Self::mit_abfrage(sample_rate, null_dauer, system_stumm_abfragen)
+    }
+
+    /// Erstellt die Erkennung mit eigener Abfrage des Betriebssystems
+    pub fn mit_abfrage(sample_rate: u32, null_dauer: Duration, abfrage: SystemAbfrage) -> Self {
+        Self {
+            null: NullSignalDetektor::neu(sample_rate, null_dauer),
+            abfrage,
+            abfrage_intervall: sample_rate.max(1) as u64,
+            seit_abfrage: None,
+            system: None,
+            stumm: false,
+        }
+    }
+
+    /// Verarbeitet einen Block Samples (roh, vor jeder DSP)
+    ///
+    /// Gibt den neuen Zustand zurueck, wenn er sich geaendert hat.
+    pub fn verarbeiten(&mut self, samples: &[f32]) -> Option<bool> {
+        self.null.verarbeiten(samples);
+
+        let seit = self
+            .seit_abfrage
+            .map_or(u64::MAX, |s| s.saturating_add(samples.len() as u64));
+        if seit >= self.abfrage_intervall {
+            self.system = (self.abfrage)();
+            self.seit_abfrage = Some(0);
+        } else {
+            self.seit_abfrage = Some(seit);
+        }
+
+        let stumm = self.system == Some(true) || self.null.ist_stumm();
+        if stumm == self.stumm {
+            return None;
+        }
+        self.stumm = stumm;
+        Some(stumm)
+    }
+
+    /// Gilt das Mikrofon gerade als hardwareseitig stumm?
+    pub fn ist_stumm(&self) -> bool {
+        self.stumm
+    }
+}
+
+/// Fragt den Mute-Zustand des Standard-Aufnahmegeraets beim System ab
+///
+/// Nur unter Windows (WASAPI) verfuegbar, sonst immer `None`.
+#[cfg(all(windows, feature = "hardware"))]
+pub fn system_stumm_abfragen() -> Option<bool> {
+    use windows::Win32::Media::Audio::Endpoints::IAudioEndpointVolume;
+    use windows::Win32::Media::Audio::{
+        eCapture, eConsole, IMMDeviceEnumerator, MMDeviceEnumerator,
+    };
+    use windows::Win32::System::Com::{
+        CoCreateInstance, CoInitializeEx, CLSCTX_ALL, COINIT_APARTMENTTHREADED,
+    };
+
+    // SAFETY: reine COM-Aufrufe; ein bereits (anders) initialisierter
+    // Thread liefert nur einen Fehlercode, COM bleibt nutzbar
+    unsafe {
+        let _ = CoInitializeEx(None, COINIT_APARTMENTTHREADED);
+        let geraete: IMMDeviceEnumerator =
+            CoCreateInstance(&MMDeviceEnumerator, None, CLSCTX_ALL).ok()?;
+        let mikrofon = geraete.GetDefaultAudioEndpoint(eCapture, eConsole).ok()?;
+        let lautstaerke: IAudioEndpointVolume = mikrofon.Activate(CLSCTX_ALL, None).ok()?;
+        lautstaerke.GetMute().ok().map(|stumm| stumm.as_bool())
+    }
+}
+
+/// Fragt den Mute-Zustand des Standard-Aufnahmegeraets beim System ab
+///
+/// Nur unter Windows (WASAPI) verfuegbar, sonst immer `None`.
+#[cfg(not(all(windows, feature = "hardware")))]
+pub fn system_stumm_abfragen() -> Option<bool> {
+    None
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 1 kHz, damit 1 ms einem Sample entspricht
+    const RATE: u32 = 1000;
+
+    fn detektor(ms: u64) -> NullSignalDetektor {
+        NullSignalDetektor::neu(RATE, Duration::from_millis(ms))
+    }
+
+    #[test]
+    fn meldet_erst_ab_der_schwelle() {
+        let mut d = detektor(1000);
+        assert_eq!(d.verarbeiten(&[0.0; 999]), None);
+        assert!(!d.ist_stumm());
+        assert_eq!(d.verarbeiten(&[0.0]), Some(true));
+        assert!(d.ist_stumm());
+        // Weitere Nullen aendern nichts mehr
+        assert_eq!(d.verarbeiten(&[0.0; 500]), None);
+    }
+
+    #[test]
+    fn leises_rauschen_ist_kein_null_signal() {
+        let mut d = detektor(1000);
+        // -140 dBFS: weit unter jeder Sprach- oder Gate-Schwelle
+        let rauschen: Vec<f32> = (0..100)
+            .map(|i| if i % 2 == 0 { 1e-7 } else { -1e-7 })
+            .collect();
+        for _ in 0..100 {
+            assert_eq!(d.verarbeiten(&rauschen), None);
+        }
+        assert!(!d.ist_stumm());
+    }
+
+    #[test]
+    fn einzelnes_sample_setzt_zaehler_zurueck() {
+        let mut d = detektor(1000);
+        d.verarbeiten(&[0.0; 900]);
+        // Nach dem Sample zaehlen nur die 50 Nullen dahinter
+        let mut block = vec![0.0; 100];
+        block[49] = 1e-6;
+        assert_eq!(d.verarbeiten(&block), None);
+        assert_eq!(d.verarbeiten(&[0.0; 949]), None);
+        assert_eq!(d.verarbeiten(&[0.0]), Some(true));
+    }
+
+    #[test]
+    fn signal_hebt_stummschaltung_sofort_auf() {
+        let mut d = detektor(1000);
+        d.verarbeiten(&[0.0; 1000]);
+        assert!(d.ist_stumm());
+        let mut block = vec![0.0; 20];
+        block[0] = 0.01;
+        assert_eq!(d.verarbeiten(&block), Some(false));
+        assert!(!d.ist_stumm());
+    }
+
+    #[test]
+    fn dauer_wird_begrenzt() {
+        // Unter dem Minimum zaehlt das Minimum
+        let mut d = detektor(10);
+        assert_eq!(d.verarbeiten(&[0.0; 499]), None);
+        assert_eq!(d.verarbeiten(&[0.0]), Some(true));
+
+        let mut d = NullSignalDetektor::neu(RATE, Duration::from_secs(600));
+        assert_eq!(d.verarbeiten(&vec![0.0; 59_999]), None);
+        assert_eq!(d.verarbeiten(&[0.0]), Some(true));
+    }
+
+    #[test]
+    fn zuruecksetzen_vergisst_verlauf() {
+        let mut d = detektor(1000);
+        d.verarbeiten(&[0.0; 1000]);
+        d.zuruecksetzen();
+        assert!(!d.ist_stumm());
+        assert_eq!(d.verarbeiten(&[0.0; 999]), None);
+    }
+
+    #[test]
+    fn systemzustand_wird_im_sekundentakt_abgefragt() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        static ABFRAGEN: AtomicUsize = AtomicUsize::new(0);
+        fn stumm() -> Option<bool> {
+            ABFRAGEN.fetch_add(1, Ordering::Relaxed);
+            Some(true)
+        }
+        let mut e = HardwareStummErkennung::mit_abfrage(RATE, STANDARD_NULL_DAUER, stumm);
+        // Erste Abfrage sofort; trotz Signal gilt der Systemzustand
+        assert_eq!(e.verarbeiten(&[0.5; 20]), Some(true));
+        assert!(e.ist_stumm());
+        for _ in 0..49 {
+            e.verarbeiten(&[0.5; 20]);
+        }
+        assert_eq!(ABFRAGEN.load(Ordering::Relaxed), 1);
+        e.verarbeiten(&[0.5; 20]);
+        assert_eq!(ABFRAGEN.load(Ordering::Relaxed), 2);
+    }
+
+    #[test]
+    fn ohne_systemzustand_entscheidet_das_null_signal() {
+        fn nie() -> Option<bool> {
+            None
+        }
+        let mut e = HardwareStummErkennung::mit_abfrage(RATE, Duration::from_secs(1), nie);
+        assert_eq!(e.verarbeiten(&[0.0; 999]), None);
+        assert_eq!(e.verarbeiten(&[0.0]), Some(true));
+        assert_eq!(e.verarbeiten(&[0.1]), Some(false));
+    }
+}
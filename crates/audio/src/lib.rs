@@ -2,38 +2,73 @@
 //!
 //! Vollstaendige Audio-Pipeline fuer Speakeasy:
 //! - Mikrofon-Capture via cpal
-//! - Lautsprecher-Playback via cpal
-//! - Opus Encoding/Decoding
+//! - Lautsprecher-Playback via cpal (Unterlauf-Erkennung, adaptiver Vorpuffer)
+//! - Opus Encoding/Decoding, FEC-Rekonstruktion einzelner Verluste beim Empfang
 //! - DSP: Noise Gate, VAD, AGC, Noise Suppression, Echo Cancellation, De-Esser
 //! - Push-to-Talk (Hold, Toggle, Voice Activation)
 //! - Auto-Kalibrierung
 //! - Per-User Lautstaerke-Kontrolle
+//! - Mischer des Empfangspfads mit begrenzter Warteschlange je Sprecher
+//! - Quellen und Senken ohne Hardware (Dateien, Puffer, Stille) und eine
+//!   Sende-Pipeline bis zum fertigen Voice-Paket
+//! - Kodierung kurzer Clips fuer das Soundboard
+//! - Erkennung einer Hardware-Stummschaltung (Headset-Taste, WASAPI unter Windows)
+//! - Warnung vor mitgesendeten Hintergrundgeraeuschen (Luefter, Musik)
+//!
+//! Alles, was Audio-Geraete anspricht, haengt am Feature `hardware`
+//! (Standard). Ohne das Feature kommt cpal gar nicht erst in den Build, etwa
+//! fuer Bots auf Servern ohne Soundkarte.
 
 pub mod calibration;
 pub mod capture;
+pub mod clip;
 pub mod codec;
+#[cfg(feature = "hardware")]
 pub mod device;
 pub mod dsp;
+pub mod empfang;
 pub mod engine;
 pub mod error;
+pub mod hardware_stumm;
+pub mod mischer;
 pub mod pipeline;
 pub mod playback;
 pub mod ptt;
+pub mod quelle;
+pub mod sample;
+pub mod sender;
+pub mod stoergeraeusch;
+pub mod unterlauf;
 pub mod volume;
 
 // Bequeme Re-Exporte der wichtigsten Typen
 pub use calibration::{calibrate_from_samples, default_calibration, CalibrationResult};
 pub use capture::{CaptureConfig, CaptureConsumer, CaptureProducer};
-pub use codec::{OpusDecoder, OpusEncoder};
+pub use clip::{clip_kodieren, wav_clip_kodieren, KodierterClip};
+pub use codec::{
+    frame_angleichen, BitrateVorgabe, CodecFehlerArt, CodecStatistik, CodecZaehler, OpusDecoder,
+    OpusEncoder, PcmuDecoder, PcmuEncoder, SprachDecoder, SprachEncoder,
+};
+#[cfg(feature = "hardware")]
 pub use device::{
     get_default_input, get_default_output, list_input_devices, list_output_devices, AudioDevice,
 };
 pub use dsp::AudioProcessor;
+pub use empfang::{EmpfangsStatistik, EmpfangsStrom};
 pub use engine::{AudioEngine, AudioEngineConfig, AudioStats};
 pub use error::{AudioError, AudioResult};
+pub use hardware_stumm::{HardwareStummErkennung, NullSignalDetektor};
+pub use mischer::{MischerKonfig, WarteschlangenStatistik, WiedergabeMischer};
 pub use pipeline::{
     build_default_capture_pipeline, build_minimal_capture_pipeline, AudioPipeline, ProcessedFrame,
 };
-pub use playback::{PlaybackConfig, PlaybackProducer};
+pub use playback::{DuckingRegler, EffektProducer, PlaybackConfig, PlaybackProducer};
 pub use ptt::{PttController, PttMode};
+pub use quelle::{
+    wav_dekodieren, wav_laden, AudioSink, AudioSource, PufferQuelle, PufferSenke, Stille,
+};
+pub use sample::GeraeteSample;
+pub use sender::{SendePipeline, SendeSchritt};
+pub use stoergeraeusch::{FrameMerkmale, StoergeraeuschErkennung, StoergeraeuschKonfig};
+pub use unterlauf::{LatenzBudget, UnterlaufKonfig, UnterlaufZaehler};
 pub use volume::VolumeController;
@@ -0,0 +1,398 @@
+//! Mischer des Empfangspfads – begrenzte Warteschlange je Sprecher
+//!
+//! Dekodierte Frames landen je SSRC in einer eigenen Warteschlange. Jeder
+//! Abspieltakt entnimmt jeder Warteschlange einen Frame und mischt alle zu
+//! einem Ausgabe-Frame.
+//!
+//! Liefert ein Stream schneller als in Echtzeit (Bot, Soundboard mit falscher
+//! Taktung), waechst nur seine Warteschlange, und zwar hoechstens bis
+//! [`MischerKonfig::max_frames`]. Darueber wird der aelteste Frame verworfen
+//! und als Ueberlauf gezaehlt – getrennt vom Netzwerkverlust, denn das Paket
+//! ist ja angekommen. Speicher und Latenz dieses Streams bleiben begrenzt,
+//! die anderen Sprecher merken davon nichts.
+//!
+//! Laeuft ein Stream [`MischerKonfig::dauerhaft_nach`] Takte in Folge ueber,
+//! meldet [`WiedergabeMischer::dekodieren_lohnt`] bei voller Warteschlange,
+//! dass der naechste Frame ohnehin verworfen wuerde. Der Aufrufer spart sich
+//! dann das Dekodieren; statt des aeltesten entfaellt der neue Frame.
+
+use std::collections::{HashMap, VecDeque};
+
+/// Standard-Obergrenze der Warteschlange je Sprecher (200ms bei 20ms-Frames)
+pub const STANDARD_MAX_FRAMES: usize = 10;
+
+/// Konfiguration des Mischers
+#[derive(Debug, Clone)]
+pub struct MischerKonfig {
+    /// Samples je Abspieltakt (und je Ausgabe-Frame)
+    pub frame_samples: usize,
+    /// Obergrenze der Warteschlange je Sprecher in Frames
+    pub max_frames: usize,
+    /// Takte mit Ueberlauf in Folge, ab denen nicht mehr dekodiert wird
+    pub dauerhaft_nach: u32,
+}
+
+impl Default for MischerKonfig {
+    /// 20ms-Takt bei 48 kHz Mono, hoechstens 10 Frames, 5 Takte Ueberlauf
+    fn default() -> Self {
+        Self {
+            frame_samples: 960,
+            max_frames: STANDARD_MAX_FRAMES,
+            dauerhaft_nach: 5,
+        }
+    }
+}
+
+/// Zaehler und Fuellstand der Warteschlange eines Sprechers
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct WarteschlangenStatistik {
+    /// Wegen voller Warteschlange verworfene Frames (kein Netzwerkverlust)
+    pub ueberlauf: u64,
+    /// Davon gar nicht erst dekodiert
+    pub nicht_dekodiert: u64,
+    /// Aktuell wartende Frames (angebrochene aufgerundet)
+    pub tiefe: usize,
+    /// Hoechster Fuellstand seit Beginn
+    pub max_tiefe: usize,
+}
+
+#[derive(Debug, Default)]
+struct Warteschlange {
+    samples: VecDeque<f32>,
+    statistik: WarteschlangenStatistik,
+    /// Ueberlauf seit dem letzten Takt
+    ueberlauf_im_takt: bool,
+    /// Takte mit Ueberlauf in Folge
+    takte_mit_ueberlauf: u32,
+}
+
+/// Mischt die Streams aller Sprecher mit begrenzter Warteschlange je SSRC
+#[derive(Debug)]
+pub struct WiedergabeMischer {
+    konfig: MischerKonfig,
+    stroeme: HashMap<u32, Warteschlange>,
+}
+
+impl WiedergabeMischer {
+    pub fn neu(konfig: MischerKonfig) -> Self {
+        Self {
+            konfig,
+            stroeme: HashMap::new(),
+        }
+    }
+
+    pub fn konfig(&self) -> &MischerKonfig {
+        &self.konfig
+    }
+
+    /// Vor dem Dekodieren: `false` = der Frame wuerde ohnehin verworfen
+    ///
+    /// Gilt nur fuer Streams, die dauerhaft ueberlaufen und deren
+    /// Warteschlange voll ist; der ausgelassene Frame zaehlt als Ueberlauf.
+    pub fn dekodieren_lohnt(&mut self, ssrc: u32) -> bool {
+        let frame = self.konfig.frame_samples;
+        let voll = self.konfig.max_frames * frame;
+        let Some(strom) = self.stroeme.get_mut(&ssrc) else {
+            return true;
+        };
+        if strom.takte_mit_ueberlauf < self.konfig.dauerhaft_nach
+            || strom.samples.len() + frame <= voll
+        {
+            return true;
+        }
+        strom.statistik.ueberlauf += 1;
+        strom.statistik.nicht_dekodiert += 1;
+        strom.ueberlauf_im_takt = true;
+        false
+    }
+
+    /// Reiht dekodierte Samples eines Sprechers ein; was ueber die
+    /// Obergrenze hinausgeht, wird vorne (aelteste Frames) verworfen
+    pub fn einreihen(&mut self, ssrc: u32, pcm: &[f32]) {
+        if pcm.is_empty() {
+            return;
+        }
+        let frame = self.konfig.frame_samples;
+        let voll = self.konfig.max_frames * frame;
+        let strom = self.stroeme.entry(ssrc).or_default();
+        strom.samples.extend(pcm);
+        while strom.samples.len() > voll {
+            strom.samples.drain(..frame);
+            strom.statistik.ueberlauf += 1;
+            strom.ueberlauf_im_takt = true;
+        }
+        let tiefe = strom.samples.len().div_ceil(frame);
+        strom.statistik.max_tiefe = strom.statistik.max_tiefe.max(tiefe);
+    }
+
+    /// Abspieltakt: entnimmt jeder Warteschlange hoechstens einen Frame und
+    /// mischt sie in `ausgabe` (Laenge [`MischerKonfig::frame_samples`])
+    ///
+    /// Liefert `false`, wenn kein Sprecher etwas beigetragen hat; `ausgabe`
+    /// ist dann Stille.
+    pub fn mischen(&mut self, ausgabe: &mut [f32]) -> bool {
+        ausgabe.fill(0.0);
+        let mut beigetragen = false;
+        for strom in self.stroeme.values_mut() {
+            if strom.ueberlauf_im_takt {
+                strom.takte_mit_ueberlauf = strom.takte_mit_ueberlauf.saturating_add(1);
+            } else {
+                strom.takte_mit_ueberlauf = 0;
+            }
+            strom.ueberlauf_im_takt = false;
+
+            let anzahl = ausgabe.len().min(strom.samples.len());
+            if anzahl == 0 {
+                continue;
+            }
+            beigetragen = true;
+            for (out, s) in ausgabe.iter_mut().zip(strom.samples.drain(..anzahl)) {
+                *out += s;
+            }
+        }
+        // Mehrere laute Sprecher: hart begrenzen, ein einzelner bleibt unveraendert
+        for s in ausgabe.iter_mut() {
+            *s = s.clamp(-1.0, 1.0);
+        }
+        beigetragen
+    }
+
+    /// Verwirft alles Wartende (z.B. Deafen); die Zaehler bleiben
+    pub fn leeren(&mut self) {
+        for strom in self.stroeme.values_mut() {
+            strom.samples.clear();
+            strom.ueberlauf_im_takt = false;
+            strom.takte_mit_ueberlauf = 0;
+        }
+    }
+
+    /// Statistik eines Sprechers; `None` = noch nichts eingereiht
+    pub fn statistik(&self, ssrc: u32) -> Option<WarteschlangenStatistik> {
+        self.stroeme.get(&ssrc).map(|strom| self.mit_tiefe(strom))
+    }
+
+    /// Statistik aller bisher gesehenen Sprecher
+    pub fn statistiken(&self) -> Vec<(u32, WarteschlangenStatistik)> {
+        let mut liste: Vec<_> = self
+            .stroeme
+            .iter()
+            .map(|(ssrc, strom)| (*ssrc, self.mit_tiefe(strom)))
+            .collect();
+        liste.sort_by_key(|(ssrc, _)| *ssrc);
+        liste
+    }
+
+    fn mit_tiefe(&self, strom: &Warteschlange) -> WarteschlangenStatistik {
+        WarteschlangenStatistik {
+            tiefe: strom.samples.len().div_ceil(self.konfig.frame_samples),
+            ..strom.statistik
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::codec::{SprachDecoder, SprachEncoder};
+    use crate::empfang::EmpfangsStrom;
+    use speakeasy_protocol::codec::AudioPreset;
+    use speakeasy_protocol::voice::{AudioCodec, PacketType, VoiceFlags, VoicePacketHeader};
+
+    const FLUT: u32 = 1;
+    const NORMAL: u32 = 2;
+
+    fn frame(wert: f32) -> Vec<f32> {
+        vec![wert; 960]
+    }
+
+    #[test]
+    fn ueberlauf_verwirft_die_aeltesten_frames() {
+        let mut mischer = WiedergabeMischer::neu(MischerKonfig::default());
+        for i in 0..13 {
+            mischer.einreihen(FLUT, &frame(i as f32 / 100.0));
+        }
+        let statistik = mischer.statistik(FLUT).unwrap();
+        assert_eq!(statistik.ueberlauf, 3);
+        assert_eq!(statistik.tiefe, STANDARD_MAX_FRAMES);
+        assert_eq!(statistik.max_tiefe, STANDARD_MAX_FRAMES);
+
+        // Frames 0..3 sind weg, als naechstes kommt Frame 3
+        let mut ausgabe = frame(0.0);
+        assert!(mischer.mischen(&mut ausgabe));
+        assert!((ausgabe[0] - 0.03).abs() < 1e-6);
+    }
+
+    #[test]
+    fn stroeme_werden_gemischt_und_begrenzt() {
+        let mut mischer = WiedergabeMischer::neu(MischerKonfig::default());
+        let mut ausgabe = frame(0.0);
+        assert!(!mischer.mischen(&mut ausgabe));
+
+        mischer.einreihen(FLUT, &frame(0.25));
+        mischer.einreihen(NORMAL, &frame(0.5));
+        assert!(mischer.mischen(&mut ausgabe));
+        assert!(ausgabe.iter().all(|s| (s - 0.75).abs() < 1e-6));
+
+        mischer.einreihen(FLUT, &frame(0.75));
+        mischer.einreihen(NORMAL, &frame(0.75));
+        mischer.mischen(&mut ausgabe);
+        assert!(ausgabe.iter().all(|s| *s == 1.0));
+    }
+
+    #[test]
+    fn leeren_behaelt_die_zaehler() {
+        let mut mischer = WiedergabeMischer::neu(MischerKonfig::default());
+        for _ in 0..12 {
+            mischer.einreihen(FLUT, &frame(0.1));
+        }
+        mischer.leeren();
+        let statistik = mischer.statistik(FLUT).unwrap();
+        assert_eq!(statistik.tiefe, 0);
+        assert_eq!(statistik.ueberlauf, 2);
+        assert!(!mischer.mischen(&mut frame(0.0)));
+    }
+
+    /// Opus-Pakete eines Sprechers mit Sinus-Ton
+    fn pakete(ssrc: u32, anzahl: u32) -> Vec<(VoicePacketHeader, Vec<u8>)> {
+        let mut encoder =
+            SprachEncoder::new(AudioCodec::Opus, AudioPreset::Balanced.config()).unwrap();
+        (0..anzahl)
+            .map(|seq| {
+                let pcm: Vec<f32> = (0..960)
+                    .map(|i| {
+                        let t = (seq * 960 + i) as f32 / 48000.0;
+                        (t * 440.0 * std::f32::consts::TAU).sin() * 0.3
+                    })
+                    .collect();
+                let payload = encoder.encode(&pcm).unwrap();
+                let header = VoicePacketHeader::new(
+                    PacketType::Audio,
+                    VoiceFlags::FEC,
+                    seq,
+                    seq * 960,
+                    ssrc,
+                );
+                (header, payload)
+            })
+            .collect()
+    }
+
+    fn strom() -> EmpfangsStrom {
+        EmpfangsStrom::neu(
+            SprachDecoder::new(AudioCodec::Opus, &AudioPreset::Balanced.config()).unwrap(),
+        )
+    }
+
+    /// Ein Paket dekodieren und einreihen, wie es der Empfangspfad tut
+    fn empfangen(
+        mischer: &mut WiedergabeMischer,
+        strom: &mut EmpfangsStrom,
+        (header, payload): &(VoicePacketHeader, Vec<u8>),
+    ) -> bool {
+        if !mischer.dekodieren_lohnt(header.ssrc) {
+            strom.ueberspringen(header.sequence);
+            return false;
+        }
+        let pcm = strom.dekodieren(header, payload).unwrap();
+        mischer.einreihen(header.ssrc, &pcm);
+        true
+    }
+
+    #[test]
+    fn doppelt_schneller_strom_bleibt_begrenzt_und_stoert_niemanden() {
+        const TAKTE: usize = 200;
+        let flut = pakete(FLUT, 2 * TAKTE as u32);
+        let normal = pakete(NORMAL, TAKTE as u32);
+        let mut flut_strom = strom();
+        let mut normal_strom = strom();
+
+        // Referenz: der normale Strom allein
+        let mut allein = WiedergabeMischer::neu(MischerKonfig::default());
+        let mut referenz_strom = strom();
+        let mut referenz = Vec::new();
+        for paket in &normal {
+            empfangen(&mut allein, &mut referenz_strom, paket);
+            let mut ausgabe = frame(0.0);
+            allein.mischen(&mut ausgabe);
+            referenz.push(ausgabe);
+        }
+
+        // Doppeltes Tempo: zwei Pakete je Abspieltakt
+        let mut mischer = WiedergabeMischer::neu(MischerKonfig::default());
+        let mut dekodiert = 0;
+        for takt in 0..TAKTE {
+            for paket in &flut[2 * takt..2 * takt + 2] {
+                if empfangen(&mut mischer, &mut flut_strom, paket) {
+                    dekodiert += 1;
+                }
+            }
+            empfangen(&mut mischer, &mut normal_strom, &normal[takt]);
+
+            let tiefe = mischer.statistik(FLUT).unwrap().tiefe;
+            assert!(tiefe <= STANDARD_MAX_FRAMES, "Speicher begrenzt");
+
+            // Anteil des normalen Stroms = Ausgabe minus Flut-Anteil
+            let mut flut_anteil: Vec<f32> = mischer.stroeme[&FLUT]
+                .samples
+                .iter()
+                .take(960)
+                .copied()
+                .collect();
+            flut_anteil.resize(960, 0.0);
+            let mut ausgabe = frame(0.0);
+            mischer.mischen(&mut ausgabe);
+            let abweichung = ausgabe
+                .iter()
+                .zip(&flut_anteil)
+                .zip(&referenz[takt])
+                .map(|((a, f), r)| (a - f - r).abs())
+                .fold(0.0f32, f32::max);
+            assert!(abweichung < 1e-5, "Takt {takt}: normaler Strom veraendert");
+        }
+
+        let flut_stat = mischer.statistik(FLUT).unwrap();
+        // Latenz: hoechstens volle Warteschlange (200ms), nie mehr
+        assert_eq!(flut_stat.max_tiefe, STANDARD_MAX_FRAMES);
+        assert!(flut_stat.tiefe >= STANDARD_MAX_FRAMES - 1);
+        // Jeder zweite Frame passt nicht, die meisten davon werden gar nicht dekodiert
+        assert!(flut_stat.ueberlauf >= TAKTE as u64 - STANDARD_MAX_FRAMES as u64);
+        assert!(flut_stat.nicht_dekodiert > flut_stat.ueberlauf / 2);
+        assert!(dekodiert < 2 * TAKTE - TAKTE / 2);
+
+        // Ueberlauf ist kein Netzwerkverlust
+        assert_eq!(flut_strom.statistik().grosse_luecken, 0);
+        assert_eq!(flut_strom.statistik().plc_verdeckt, 0);
+        assert_eq!(flut_strom.statistik().fec_rekonstruiert, 0);
+
+        let normal_stat = mischer.statistik(NORMAL).unwrap();
+        assert_eq!(normal_stat.ueberlauf, 0);
+        assert!(normal_stat.max_tiefe <= 1);
+    }
+
+    #[test]
+    fn nach_der_flut_wird_wieder_dekodiert() {
+        let flut = pakete(FLUT, 60);
+        let mut flut_strom = strom();
+        let mut mischer = WiedergabeMischer::neu(MischerKonfig::default());
+        for takt in 0..20 {
+            for paket in &flut[2 * takt..2 * takt + 2] {
+                empfangen(&mut mischer, &mut flut_strom, paket);
+            }
+            mischer.mischen(&mut frame(0.0));
+        }
+        let vorher = mischer.statistik(FLUT).unwrap();
+        assert!(vorher.nicht_dekodiert > 0);
+
+        // Wieder Echtzeit: ein Paket je Takt, nichts geht mehr verloren
+        for paket in &flut[40..60] {
+            assert!(empfangen(&mut mischer, &mut flut_strom, paket));
+            mischer.mischen(&mut frame(0.0));
+        }
+        let nachher = mischer.statistik(FLUT).unwrap();
+        assert_eq!(nachher.ueberlauf, vorher.ueberlauf);
+    }
+}
@@ -4,6 +4,7 @@
 //! Trennung zwischen Capture-Pipeline (Mikrofon -> Encode) und
 //! Playback-Pipeline (Decode -> Volume -> Output).
 
+use crate::dsp::echo_cancel::ReferenzQuelle;
 use crate::dsp::AudioProcessor;
 
 /// Ergebnis eines verarbeiteten Frames
@@ -99,7 +100,10 @@ impl AudioPipeline {
 /// Erstellt die Standard-Capture-Pipeline
 ///
 /// Reihenfolge: NoiseGate -> NoiseSuppression -> AGC -> EchoCancellation -> DeEsser
-pub fn build_default_capture_pipeline() -> AudioPipeline {
+///
+/// Mit `referenz` liest die Echo Cancellation je Frame, was der Playback-Pfad
+/// gerade ausgibt; ohne hat sie kein Fernsignal und subtrahiert nur Stille.
+pub fn build_default_capture_pipeline(referenz: Option<ReferenzQuelle>) -> AudioPipeline {
     use crate::dsp::{
         agc::{Agc, AgcConfig},
         deesser::{DeEsser, DeEsserConfig},
@@ -108,11 +112,16 @@ pub fn build_default_capture_pipeline() -> AudioPipeline {
         noise_suppression::{NoiseSuppressor, SuppressionLevel},
     };
 
+    let mut echo = EchoCanceller::new(EchoCancelConfig::default());
+    if let Some(referenz) = referenz {
+        echo = echo.mit_referenz(referenz);
+    }
+
     AudioPipeline::new(vec![
         Box::new(NoiseGate::new(NoiseGateConfig::default())),
         Box::new(NoiseSuppressor::new(SuppressionLevel::Medium)),
         Box::new(Agc::new(AgcConfig::default())),
-        Box::new(EchoCanceller::new(EchoCancelConfig::default())),
+        Box::new(echo),
         Box::new(DeEsser::new(DeEsserConfig::default())),
     ])
 }
@@ -168,13 +177,13 @@ mod tests {
 
     #[test]
     fn pipeline_reset_all() {
-        let mut pipeline = build_default_capture_pipeline();
+        let mut pipeline = build_default_capture_pipeline(None);
         pipeline.reset_all(); // Darf nicht panic
     }
 
     #[test]
     fn pipeline_set_all_disabled() {
-        let mut pipeline = build_default_capture_pipeline();
+        let mut pipeline = build_default_capture_pipeline(None);
         pipeline.set_all_enabled(false);
         let input = vec![0.001f32; 480];
         let result = pipeline.process_frame(&input);
@@ -194,10 +203,20 @@ mod tests {
 
     #[test]
     fn default_capture_pipeline_hat_5_prozessoren() {
-        let pipeline = build_default_capture_pipeline();
+        let pipeline = build_default_capture_pipeline(None);
         assert_eq!(pipeline.len(), 5);
     }
 
+    #[test]
+    fn default_capture_pipeline_liest_die_echo_referenz() {
+        let (mut playback, quelle) = crate::dsp::echo_cancel::echo_referenz(4800);
+        let mut pipeline = build_default_capture_pipeline(Some(quelle));
+        assert_eq!(playback.einspeisen(&[0.2f32; 960]), 960);
+        pipeline.process_frame(&[0.0f32; 960]);
+        // Die Echo Cancellation hat den Frame abgeholt
+        assert_eq!(playback.einspeisen(&[0.2f32; 4800]), 4800);
+    }
+
     #[test]
     fn minimal_pipeline_hat_2_prozessoren() {
         let pipeline = build_minimal_capture_pipeline();
@@ -206,7 +225,7 @@ mod tests {
 
     #[test]
     fn pipeline_verarbeitet_frame_laenge_erhalten() {
-        let mut pipeline = build_default_capture_pipeline();
+        let mut pipeline = build_default_capture_pipeline(None);
         let input = vec![0.1f32; 960];
         let result = pipeline.process_frame(&input);
         assert_eq!(
@@ -2,14 +2,39 @@
 //!
 //! Oeffnet einen cpal OutputStream und liest Samples aus einem
 //! lock-free Ring-Buffer. Unterstuetzt Mixing mehrerer Quellen.
+//!
+//! Fehlende Samples behandelt [`UnterlaufBehandlung`]: Stille mit kurzer
+//! Ausblendung, danach adaptiver Vorpuffer (siehe [`crate::unterlauf`]).
+//!
+//! Optional kann ein zweiter Ring-Buffer fuer Effekte (Event-Sounds)
+//! geoeffnet werden. Dessen Samples werden im Callback zur Sprache
+//! addiert; die Sprache wird dabei hoechstens um den Ducking-Anteil
+//! abgesenkt.
+//!
+//! Erwartet das Geraet i16- oder u16-Samples, wird im Callback aus f32
+//! gewandelt (siehe [`crate::sample`]).
+//!
+//! Ohne Feature `hardware` bleiben Konfiguration, Ring-Buffer-Typen und das
+//! Mischen; als Ausgabe dient dann ein [`AudioSink`](crate::quelle::AudioSink).
+
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
 
-use cpal::traits::{DeviceTrait, StreamTrait};
-use cpal::{Device, SampleFormat, Stream, StreamConfig};
-use ringbuf::traits::{Consumer, Split};
-use ringbuf::{HeapCons, HeapProd, HeapRb};
-use tracing::{debug, error, warn};
+use ringbuf::{HeapCons, HeapProd};
 
-use crate::error::{AudioError, AudioResult};
+use crate::unterlauf::{LatenzBudget, UnterlaufKonfig, UnterlaufZaehler};
+
+#[cfg(feature = "hardware")]
+use {
+    crate::error::{AudioError, AudioResult},
+    crate::sample::{self, GeraeteSample},
+    crate::unterlauf::UnterlaufBehandlung,
+    cpal::traits::{DeviceTrait, StreamTrait},
+    cpal::{Device, SampleFormat, SizedSample, Stream, StreamConfig},
+    ringbuf::traits::{Consumer, Observer, Split},
+    ringbuf::HeapRb,
+    tracing::{debug, error},
+};
 
 /// Konfiguration fuer den Audio-Playback
 #[derive(Debug, Clone)]
@@ -20,6 +45,12 @@ pub struct PlaybackConfig {
     pub channels: u16,
     /// Ring-Buffer Kapazitaet in Samples
     pub buffer_size: usize,
+    /// Unterlauf-Behandlung und adaptiver Vorpuffer
+    pub unterlauf: UnterlaufKonfig,
+    /// Mit dem Jitter-Buffer geteiltes Latenzbudget
+    pub budget: LatenzBudget,
+    /// Zaehlt Unterlaeufe im Playback-Callback
+    pub unterlauf_zaehler: UnterlaufZaehler,
 }
 
 impl Default for PlaybackConfig {
@@ -28,6 +59,9 @@ impl Default for PlaybackConfig {
             sample_rate: 48000,
             channels: 1,
             buffer_size: 48000 * 2,
+            unterlauf: UnterlaufKonfig::default(),
+            budget: LatenzBudget::default(),
+            unterlauf_zaehler: UnterlaufZaehler::default(),
         }
     }
 }
@@ -37,12 +71,124 @@ pub type PlaybackProducer = HeapProd<f32>;
 /// Konsumiert Samples im cpal-Callback
 pub type PlaybackConsumer = HeapCons<f32>;
 
+/// Produziert Effekt-Samples (Event-Sounds), die zur Sprache gemischt werden
+pub type EffektProducer = HeapProd<f32>;
+
+/// Anteil (0.0 - 1.0), um den die Sprache waehrend eines Effekts abgesenkt wird
+///
+/// Lock-free zwischen Steuer-Thread und cpal-Callback geteilt.
+#[derive(Debug, Clone, Default)]
+pub struct DuckingRegler(Arc<AtomicU32>);
+
+impl DuckingRegler {
+    pub fn neu(anteil: f32) -> Self {
+        let regler = Self::default();
+        regler.setzen(anteil);
+        regler
+    }
+
+    pub fn setzen(&self, anteil: f32) {
+        self.0
+            .store(anteil.clamp(0.0, 1.0).to_bits(), Ordering::Relaxed);
+    }
+
+    pub fn wert(&self) -> f32 {
+        f32::from_bits(self.0.load(Ordering::Relaxed))
+    }
+}
+
+/// Mischt Effekt-Samples in den Sprach-Puffer
+///
+/// Solange Effekt-Samples anliegen, wird die Sprache um `ducking`
+/// abgesenkt. Das Ergebnis wird auf [-1.0, 1.0] begrenzt.
+pub fn effekte_mischen(sprache: &mut [f32], effekte: &[f32], ducking: f32) {
+    let sprach_gain = 1.0 - ducking.clamp(0.0, 1.0);
+    for (s, e) in sprache.iter_mut().zip(effekte) {
+        *s = (*s * sprach_gain + e).clamp(-1.0, 1.0);
+    }
+}
+
+/// Effekt-Quelle im cpal-Callback
+#[cfg(feature = "hardware")]
+struct EffektQuelle {
+    consumer: PlaybackConsumer,
+    ducking: DuckingRegler,
+    puffer: Vec<f32>,
+}
+
+#[cfg(feature = "hardware")]
+impl EffektQuelle {
+    fn mischen(&mut self, data: &mut [f32]) {
+        if self.consumer.is_empty() {
+            return;
+        }
+        if self.puffer.len() < data.len() {
+            self.puffer.resize(data.len(), 0.0);
+        }
+        let gelesen = self.consumer.pop_slice(&mut self.puffer[..data.len()]);
+        effekte_mischen(
+            &mut data[..gelesen],
+            &self.puffer[..gelesen],
+            self.ducking.wert(),
+        );
+    }
+}
+
+/// Zustand des Playback-Callbacks (arbeitet in f32)
+#[cfg(feature = "hardware")]
+struct Ausgabe {
+    consumer: PlaybackConsumer,
+    unterlauf: UnterlaufBehandlung,
+    effekte: Option<EffektQuelle>,
+}
+
+#[cfg(feature = "hardware")]
+impl Ausgabe {
+    fn fuellen(&mut self, data: &mut [f32]) {
+        self.unterlauf.fuellen(&mut self.consumer, data);
+        if let Some(ref mut quelle) = self.effekte {
+            quelle.mischen(data);
+        }
+    }
+}
+
+/// Baut den Output-Stream fuer Geraete-Samples vom Typ `T`
+///
+/// Gefuellt wird ein f32-Puffer, der im Callback gewandelt wird.
+#[cfg(feature = "hardware")]
+fn ausgabe_bauen<T>(
+    device: &Device,
+    stream_config: &StreamConfig,
+    mut ausgabe: Ausgabe,
+) -> AudioResult<Stream>
+where
+    T: GeraeteSample + SizedSample,
+{
+    let err_fn = |err| error!("Playback-Fehler: {}", err);
+    let mut floats: Vec<f32> = Vec::new();
+
+    device
+        .build_output_stream(
+            stream_config,
+            move |data: &mut [T], _| {
+                floats.resize(data.len(), 0.0);
+                ausgabe.fuellen(&mut floats);
+                sample::aus_f32(&floats, data);
+            },
+            err_fn,
+            None,
+        )
+        .map_err(|e| AudioError::StreamFehler(e.to_string()))
+}
+
 /// Audio-Playback-Stream
+#[cfg(feature = "hardware")]
 pub struct PlaybackStream {
     _stream: Stream,
     config: PlaybackConfig,
 }
 
+#[cfg(feature = "hardware")]
 impl PlaybackStream {
     pub fn config(&self) -> &PlaybackConfig {
         &self.config
@@ -53,9 +199,39 @@ impl PlaybackStream {
 ///
 /// Gibt den Stream und den Ring-Buffer Producer zurueck.
 /// Der Consumer laeuft im cpal-Callback-Thread.
+#[cfg(feature = "hardware")]
 pub fn open_playback_stream(
     device: &Device,
     config: PlaybackConfig,
+) -> AudioResult<(PlaybackStream, PlaybackProducer)> {
+    oeffnen(device, config, None)
+}
+
+/// Oeffnet einen Playback-Stream mit zusaetzlicher Effekt-Quelle.
+///
+/// Gibt zusaetzlich den Producer fuer Effekt-Samples zurueck. Der
+/// `DuckingRegler` kann jederzeit angepasst werden.
+#[cfg(feature = "hardware")]
+pub fn open_playback_stream_mit_effekten(
+    device: &Device,
+    config: PlaybackConfig,
+    ducking: DuckingRegler,
+) -> AudioResult<(PlaybackStream, PlaybackProducer, EffektProducer)> {
+    let (effekt_producer, effekt_consumer) = HeapRb::<f32>::new(config.buffer_size).split();
+    let quelle = EffektQuelle {
+        consumer: effekt_consumer,
+        ducking,
+        puffer: Vec::new(),
+    };
+    let (stream, producer) = oeffnen(device, config, Some(quelle))?;
+    Ok((stream, producer, effekt_producer))
+}
+
+#[cfg(feature = "hardware")]
+fn oeffnen(
+    device: &Device,
+    config: PlaybackConfig,
+    effekte: Option<EffektQuelle>,
 ) -> AudioResult<(PlaybackStream, PlaybackProducer)> {
     let stream_config = StreamConfig {
         channels: config.channels,
@@ -64,57 +240,58 @@ pub fn open_playback_stream(
     };
 
     let rb = HeapRb::<f32>::new(config.buffer_size);
-    let (producer, mut consumer) = rb.split();
+    let (producer, consumer) = rb.split();
 
-    let err_fn = |err| error!("Playback-Fehler: {}", err);
+    let unterlauf = UnterlaufBehandlung::neu(
+        config.unterlauf.clone(),
+        config.budget.clone(),
+        config.unterlauf_zaehler.clone(),
+    );
 
-    let supported = device
+    let angebote: Vec<SampleFormat> = device
         .supported_output_configs()
         .map_err(|e| AudioError::StreamFehler(e.to_string()))?
-        .find(|c| {
+        .filter(|c| {
             c.min_sample_rate().0 <= config.sample_rate
                 && c.max_sample_rate().0 >= config.sample_rate
                 && c.channels() >= config.channels
-        });
-
-    let sample_format = supported
+        })
         .map(|c| c.sample_format())
-        .unwrap_or(SampleFormat::F32);
+        .collect();
+    let nativ = device
+        .default_output_config()
+        .ok()
+        .map(|c| c.sample_format());
 
+    let sample_format = if angebote.is_empty() {
+        SampleFormat::F32
+    } else {
+        sample::format_waehlen(nativ, &angebote).ok_or_else(|| {
+            AudioError::StreamFehler(format!(
+                "Nicht unterstuetztes Sample-Format: {:?}",
+                angebote
+            ))
+        })?
+    };
+
+    let err_fn = |err| error!("Playback-Fehler: {}", err);
+    let mut ausgabe = Ausgabe {
+        consumer,
+        unterlauf,
+        effekte,
+    };
     let stream = match sample_format {
         SampleFormat::F32 => device
             .build_output_stream(
                 &stream_config,
-                move |data: &mut [f32], _| {
-                    let read = consumer.pop_slice(data);
-                    // Stille fuer fehlende Samples
-                    if read < data.len() {
-                        warn!("Playback Underrun: {} Samples fehlen", data.len() - read);
-                        data[read..].fill(0.0);
-                    }
-                },
-                err_fn,
-                None,
-            )
-            .map_err(|e| AudioError::StreamFehler(e.to_string()))?,
-        SampleFormat::I16 => device
-            .build_output_stream(
-                &stream_config,
-                move |data: &mut [i16], _| {
-                    let mut float_buf = vec![0.0f32; data.len()];
-                    let read = consumer.pop_slice(&mut float_buf);
-                    if read < data.len() {
-                        warn!("Playback Underrun");
-                    }
-                    for (out, s) in data.iter_mut().zip(float_buf.iter()) {
-                        *out =
-                            (*s * i16::MAX as f32).clamp(i16::MIN as f32, i16::MAX as f32) as i16;
-                    }
-                },
+                move |data: &mut [f32], _| ausgabe.fuellen(data),
                 err_fn,
                 None,
             )
             .map_err(|e| AudioError::StreamFehler(e.to_string()))?,
+        SampleFormat::I16 => ausgabe_bauen::<i16>(device, &stream_config, ausgabe)?,
+        SampleFormat::U16 => ausgabe_bauen::<u16>(device, &stream_config, ausgabe)?,
+        SampleFormat::U8 => ausgabe_bauen::<u8>(device, &stream_config, ausgabe)?,
         _ => {
             return Err(AudioError::StreamFehler(format!(
                 "Nicht unterstuetztes Sample-Format: {:?}",
@@ -128,8 +305,8 @@ pub fn open_playback_stream(
         .map_err(|e| AudioError::StreamFehler(e.to_string()))?;
 
     debug!(
-        "Playback-Stream geoeffnet: {}Hz {}ch",
-        config.sample_rate, config.channels
+        "Playback-Stream geoeffnet: {}Hz {}ch {:?}",
+        config.sample_rate, config.channels, sample_format
     );
 
     Ok((
@@ -144,7 +321,6 @@ pub fn open_playback_stream(
 #[cfg(test)]
 mod tests {
     use super::*;
-    use cpal::traits::HostTrait;
 
     #[test]
     fn playback_config_default() {
@@ -155,8 +331,40 @@ mod tests {
     }
 
     #[test]
+    fn effekte_ohne_ducking_addieren() {
+        let mut sprache = vec![0.25f32; 4];
+        effekte_mischen(&mut sprache, &[0.5, 0.5], 0.0);
+        assert_eq!(sprache, vec![0.75, 0.75, 0.25, 0.25]);
+    }
+
+    #[test]
+    fn effekte_ducking_begrenzt_absenkung() {
+        let mut sprache = vec![0.8f32; 2];
+        effekte_mischen(&mut sprache, &[0.0, 0.0], 0.25);
+        assert!((sprache[0] - 0.6).abs() < 1e-6);
+    }
+
+    #[test]
+    fn effekte_mischen_clippt() {
+        let mut sprache = vec![0.9f32, -0.9];
+        effekte_mischen(&mut sprache, &[0.5, -0.5], 0.0);
+        assert_eq!(sprache, vec![1.0, -1.0]);
+    }
+
+    #[test]
+    fn ducking_regler_begrenzt_wert() {
+        let regler = DuckingRegler::neu(1.5);
+        assert_eq!(regler.wert(), 1.0);
+        regler.setzen(0.3);
+        assert!((regler.wert() - 0.3).abs() < f32::EPSILON);
+    }
+
+    #[test]
+    #[cfg(feature = "hardware")]
     #[ignore = "Benoetigt Audio-Hardware"]
     fn playback_stream_oeffnen() {
+        use cpal::traits::HostTrait;
+
         let host = cpal::default_host();
         if let Some(device) = host.default_output_device() {
             let config = PlaybackConfig::default();
@@ -0,0 +1,318 @@
+//! Audio-Quellen und -Senken ohne Hardware-Bezug
+//!
+//! [`AudioSource`] liefert Samples fuer die Sende-Seite, [`AudioSink`] nimmt
+//! dekodierte Samples auf. Beide arbeiten wie die restliche Pipeline mit
+//! 48 kHz Mono f32. Mikrofon und Lautsprecher sind nur eine Implementierung
+//! (ueber die Ring-Buffer aus Capture und Playback); Bots nutzen Dateien,
+//! Puffer oder Stille.
+
+use std::path::Path;
+use std::sync::Arc;
+
+use parking_lot::Mutex;
+use ringbuf::traits::{Consumer, Producer};
+
+use crate::capture::CaptureConsumer;
+use crate::error::{AudioError, AudioResult};
+use crate::playback::PlaybackProducer;
+
+/// Abtastrate, die Quellen liefern und Senken erwarten
+pub const SAMPLE_RATE: u32 = 48000;
+
+/// Liefert 48-kHz-Mono-Samples fuer die Sende-Seite
+pub trait AudioSource: Send {
+    /// Fuellt `buf` so weit wie moeglich und gibt die Anzahl Samples zurueck
+    ///
+    /// `0` heisst "gerade nichts verfuegbar"; ob die Quelle endgueltig leer
+    /// ist, sagt [`is_exhausted`](Self::is_exhausted).
+    fn read(&mut self, buf: &mut [f32]) -> usize;
+
+    /// Liefert die Quelle nie wieder Samples?
+    fn is_exhausted(&self) -> bool {
+        false
+    }
+}
+
+/// Nimmt 48-kHz-Mono-Samples von der Empfangs-Seite auf
+pub trait AudioSink: Send {
+    /// Schreibt Samples und gibt die Anzahl der uebernommenen zurueck
+    fn write(&mut self, samples: &[f32]) -> usize;
+}
+
+/// Mikrofon: Samples aus dem Capture-Ring-Buffer
+impl AudioSource for CaptureConsumer {
+    fn read(&mut self, buf: &mut [f32]) -> usize {
+        self.pop_slice(buf)
+    }
+}
+
+/// Lautsprecher: Samples in den Playback-Ring-Buffer
+impl AudioSink for PlaybackProducer {
+    fn write(&mut self, samples: &[f32]) -> usize {
+        self.push_slice(samples)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Stille
+// ---------------------------------------------------------------------------
+
+/// Endlose Stille, z.B. fuer Bots, die nur zuhoeren
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Stille;
+
+impl AudioSource for Stille {
+    fn read(&mut self, buf: &mut [f32]) -> usize {
+        buf.fill(0.0);
+        buf.len()
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Puffer
+// ---------------------------------------------------------------------------
+
+/// Quelle aus einem Sample-Puffer, optional in Schleife
+#[derive(Debug, Clone)]
+pub struct PufferQuelle {
+    samples: Vec<f32>,
+    position: usize,
+    schleife: bool,
+}
+
+impl PufferQuelle {
+    /// Spielt `samples` einmal ab
+    pub fn neu(samples: Vec<f32>) -> Self {
+        Self {
+            samples,
+            position: 0,
+            schleife: false,
+        }
+    }
+
+    /// Spielt `samples` endlos ab
+    pub fn schleife(samples: Vec<f32>) -> Self {
+        Self {
+            schleife: true,
+            ..Self::neu(samples)
+        }
+    }
+}
+
+impl AudioSource for PufferQuelle {
+    fn read(&mut self, buf: &mut [f32]) -> usize {
+        let mut geschrieben = 0;
+        while geschrieben < buf.len() && !self.samples.is_empty() {
+            if self.position == self.samples.len() {
+                if !self.schleife {
+                    break;
+                }
+                self.position = 0;
+            }
+            let n = (buf.len() - geschrieben).min(self.samples.len() - self.position);
+            buf[geschrieben..geschrieben + n]
+                .copy_from_slice(&self.samples[self.position..self.position + n]);
+            self.position += n;
+            geschrieben += n;
+        }
+        geschrieben
+    }
+
+    fn is_exhausted(&self) -> bool {
+        self.samples.is_empty() || (!self.schleife && self.position == self.samples.len())
+    }
+}
+
+/// Senke, die Samples bis zu einer Obergrenze sammelt
+///
+/// Klone teilen sich den Puffer, sodass der Inhalt auch nach Uebergabe an
+/// einen Task gelesen werden kann.
+#[derive(Debug, Clone)]
+pub struct PufferSenke {
+    samples: Arc<Mutex<Vec<f32>>>,
+    kapazitaet: usize,
+}
+
+impl PufferSenke {
+    /// Neue Senke, die hoechstens `kapazitaet` Samples aufnimmt
+    pub fn neu(kapazitaet: usize) -> Self {
+        Self {
+            samples: Arc::new(Mutex::new(Vec::new())),
+            kapazitaet,
+        }
+    }
+
+    /// Kopie der bisher gesammelten Samples
+    pub fn samples(&self) -> Vec<f32> {
+        self.samples.lock().clone()
+    }
+
+    /// Anzahl der gesammelten Samples
+    pub fn len(&self) -> usize {
+        self.samples.lock().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl AudioSink for PufferSenke {
+    fn write(&mut self, samples: &[f32]) -> usize {
+        let mut puffer = self.samples.lock();
+        let n = samples.len().min(self.kapazitaet - puffer.len());
+        puffer.extend_from_slice(&samples[..n]);
+        n
+    }
+}
+
+// ---------------------------------------------------------------------------
+// WAV-Dateien
+// ---------------------------------------------------------------------------
+
+/// Laedt eine WAV-Datei als 48-kHz-Mono-Samples
+///
+/// Integer- und Float-Formate werden nach f32 gewandelt, mehrere Kanaele
+/// gemittelt und andere Abtastraten linear auf 48 kHz umgerechnet.
+pub fn wav_laden(pfad: impl AsRef<Path>) -> AudioResult<Vec<f32>> {
+    wav_lesen(hound::WavReader::open(pfad).map_err(wav_fehler)?)
+}
+
+/// Wie [`wav_laden`], aber aus einer WAV-Datei im Speicher
+pub fn wav_dekodieren(daten: &[u8]) -> AudioResult<Vec<f32>> {
+    wav_lesen(hound::WavReader::new(std::io::Cursor::new(daten)).map_err(wav_fehler)?)
+}
+
+fn wav_lesen<R: std::io::Read>(reader: hound::WavReader<R>) -> AudioResult<Vec<f32>> {
+    let spec = reader.spec();
+    if spec.channels == 0 {
+        return Err(AudioError::Konfiguration("WAV-Datei ohne Kanaele".into()));
+    }
+
+    let verschachtelt: Vec<f32> = match spec.sample_format {
+        hound::SampleFormat::Float => reader
+            .into_samples::<f32>()
+            .collect::<Result<_, _>>()
+            .map_err(wav_fehler)?,
+        hound::SampleFormat::Int => {
+            let skala = (1i64 << (spec.bits_per_sample.clamp(1, 32) - 1)) as f32;
+            reader
+                .into_samples::<i32>()
+                .map(|s| s.map(|s| s as f32 / skala))
+                .collect::<Result<_, _>>()
+                .map_err(wav_fehler)?
+        }
+    };
+
+    let mono: Vec<f32> = verschachtelt
+        .chunks(spec.channels as usize)
+        .map(|frame| frame.iter().sum::<f32>() / frame.len() as f32)
+        .collect();
+    Ok(resample_linear(&mono, spec.sample_rate, SAMPLE_RATE))
+}
+
+fn wav_fehler(e: hound::Error) -> AudioError {
+    match e {
+        hound::Error::IoError(io) => AudioError::Io(io),
+        other => AudioError::Konfiguration(format!("WAV-Datei nicht lesbar: {other}")),
+    }
+}
+
+/// Lineare Interpolation von `von` Hz auf `nach` Hz
+fn resample_linear(samples: &[f32], von: u32, nach: u32) -> Vec<f32> {
+    if von == nach || samples.is_empty() || von == 0 {
+        return samples.to_vec();
+    }
+    let laenge = (samples.len() as u64 * nach as u64 / von as u64) as usize;
+    let schritt = von as f64 / nach as f64;
+    (0..laenge)
+        .map(|i| {
+            let pos = i as f64 * schritt;
+            let index = pos as usize;
+            let anteil = (pos - index as f64) as f32;
+            let a = samples[index.min(samples.len() - 1)];
+            let b = samples[(index + 1).min(samples.len() - 1)];
+            a + (b - a) * anteil
+        })
+        .collect()
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ringbuf::{traits::Split, HeapRb};
+
+    fn temp_wav(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("speakeasy-{}-{name}", uuid::Uuid::new_v4()))
+    }
+
+    #[test]
+    fn puffer_quelle_einmal_und_schleife() {
+        let mut einmal = PufferQuelle::neu(vec![0.1, 0.2, 0.3]);
+        let mut buf = [0.0f32; 2];
+        assert_eq!(einmal.read(&mut buf), 2);
+        assert_eq!(einmal.read(&mut buf), 1);
+        assert_eq!(buf[0], 0.3);
+        assert!(einmal.is_exhausted());
+        assert_eq!(einmal.read(&mut buf), 0);
+
+        let mut schleife = PufferQuelle::schleife(vec![0.1, 0.2, 0.3]);
+        let mut buf = [0.0f32; 7];
+        assert_eq!(schleife.read(&mut buf), 7);
+        assert_eq!(buf, [0.1, 0.2, 0.3, 0.1, 0.2, 0.3, 0.1]);
+        assert!(!schleife.is_exhausted());
+
+        assert!(PufferQuelle::schleife(Vec::new()).is_exhausted());
+    }
+
+    #[test]
+    fn puffer_senke_begrenzt_und_teilt() {
+        let senke = PufferSenke::neu(4);
+        let mut schreiber = senke.clone();
+        assert_eq!(schreiber.write(&[0.5; 3]), 3);
+        assert_eq!(schreiber.write(&[0.5; 3]), 1);
+        assert_eq!(senke.len(), 4);
+    }
+
+    #[test]
+    fn ring_buffer_als_quelle_und_senke() {
+        let (mut producer, mut consumer) = HeapRb::<f32>::new(8).split();
+        assert_eq!(AudioSink::write(&mut producer, &[0.25; 5]), 5);
+        let mut buf = [0.0f32; 8];
+        assert_eq!(AudioSource::read(&mut consumer, &mut buf), 5);
+        assert!(!consumer.is_exhausted());
+    }
+
+    #[test]
+    fn wav_mit_stereo_und_16khz_wird_umgerechnet() {
+        let pfad = temp_wav("stereo.wav");
+        let spec = hound::WavSpec {
+            channels: 2,
+            sample_rate: 16000,
+            bits_per_sample: 16,
+            sample_format: hound::SampleFormat::Int,
+        };
+        let mut writer = hound::WavWriter::create(&pfad, spec).unwrap();
+        for _ in 0..160 {
+            writer.write_sample(i16::MAX / 2).unwrap();
+            writer.write_sample(0i16).unwrap();
+        }
+        writer.finalize().unwrap();
+
+        let samples = wav_laden(&pfad).unwrap();
+        std::fs::remove_file(&pfad).unwrap();
+        // 10ms bei 16 kHz -> 480 Samples bei 48 kHz
+        assert_eq!(samples.len(), 480);
+        assert!(samples.iter().all(|s| (s - 0.25).abs() < 0.01));
+    }
+
+    #[test]
+    fn wav_fehlt_ergibt_io_fehler() {
+        let fehler = wav_laden(temp_wav("fehlt.wav")).unwrap_err();
+        assert!(matches!(fehler, AudioError::Io(_)));
+    }
+}
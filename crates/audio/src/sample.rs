@@ -0,0 +1,159 @@
+//! Sample-Formate der Audio-Geraete
+//!
+//! Die Pipeline rechnet durchgehend mit f32 in [-1.0, 1.0]. Viele Geraete
+//! (WASAPI, aeltere ALSA-Treiber) liefern bzw. erwarten aber nur i16 oder
+//! u16. Die Umrechnung passiert im cpal-Callback; das Format steht beim
+//! Oeffnen des Streams fest, der Callback ist generisch ueber
+//! [`GeraeteSample`] statt pro Sample zu verzweigen.
+//!
+//! Ganzzahlige Formate werden mit 2^(Bits-1) skaliert: i16 -> f32 -> i16 ist
+//! verlustfrei, Vollausschlag (`i16::MIN`) wird exakt -1.0. Beim Zurueck-
+//! wandeln wird gerundet und auf den Wertebereich begrenzt, +1.0 ergibt
+//! also `i16::MAX` statt eines Ueberlaufs.
+
+#[cfg(feature = "hardware")]
+use cpal::SampleFormat;
+
+/// Sample-Typ eines Audio-Geraets
+pub trait GeraeteSample: Copy + Send + 'static {
+    /// Wandelt in die Pipeline-Darstellung
+    fn nach_f32(self) -> f32;
+    /// Wandelt aus der Pipeline-Darstellung (begrenzt auf den Wertebereich)
+    fn aus_f32(wert: f32) -> Self;
+}
+
+impl GeraeteSample for f32 {
+    #[inline]
+    fn nach_f32(self) -> f32 {
+        self
+    }
+
+    #[inline]
+    fn aus_f32(wert: f32) -> Self {
+        wert
+    }
+}
+
+impl GeraeteSample for i16 {
+    #[inline]
+    fn nach_f32(self) -> f32 {
+        self as f32 / 32768.0
+    }
+
+    #[inline]
+    fn aus_f32(wert: f32) -> Self {
+        (wert * 32768.0).round().clamp(-32768.0, 32767.0) as i16
+    }
+}
+
+impl GeraeteSample for u16 {
+    #[inline]
+    fn nach_f32(self) -> f32 {
+        (self as f32 - 32768.0) / 32768.0
+    }
+
+    #[inline]
+    fn aus_f32(wert: f32) -> Self {
+        ((wert * 32768.0).round().clamp(-32768.0, 32767.0) + 32768.0) as u16
+    }
+}
+
+impl GeraeteSample for u8 {
+    #[inline]
+    fn nach_f32(self) -> f32 {
+        (self as f32 - 128.0) / 128.0
+    }
+
+    #[inline]
+    fn aus_f32(wert: f32) -> Self {
+        ((wert * 128.0).round().clamp(-128.0, 127.0) + 128.0) as u8
+    }
+}
+
+/// Wandelt Geraete-Samples in `ziel` (vorheriger Inhalt wird verworfen)
+///
+/// `ziel` behaelt seine Kapazitaet, im Callback wird also nach dem ersten
+/// Durchlauf nicht mehr alloziert.
+pub fn in_f32<T: GeraeteSample>(quelle: &[T], ziel: &mut Vec<f32>) {
+    ziel.clear();
+    ziel.extend(quelle.iter().map(|&s| s.nach_f32()));
+}
+
+/// Wandelt Pipeline-Samples in Geraete-Samples
+pub fn aus_f32<T: GeraeteSample>(quelle: &[f32], ziel: &mut [T]) {
+    for (aus, &s) in ziel.iter_mut().zip(quelle) {
+        *aus = T::aus_f32(s);
+    }
+}
+
+/// Waehlt das Sample-Format fuer einen Stream
+///
+/// Bevorzugt das native Format des Geraets, sofern es bei der gewuenschten
+/// Rate angeboten wird, damit nur einmal (im Callback) umgerechnet wird.
+/// Sonst das erste passende Angebot in der Reihenfolge f32, i16, u16, u8.
+/// `None`, wenn kein passendes Angebot umrechenbar ist.
+#[cfg(feature = "hardware")]
+pub fn format_waehlen(
+    nativ: Option<SampleFormat>,
+    angebote: &[SampleFormat],
+) -> Option<SampleFormat> {
+    const UMRECHENBAR: [SampleFormat; 4] = [
+        SampleFormat::F32,
+        SampleFormat::I16,
+        SampleFormat::U16,
+        SampleFormat::U8,
+    ];
+    nativ
+        .filter(|f| UMRECHENBAR.contains(f) && angebote.contains(f))
+        .or_else(|| UMRECHENBAR.into_iter().find(|f| angebote.contains(f)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn i16_rundlauf_innerhalb_eines_lsb() {
+        // Synthetischer Sweep ueber den ganzen Wertebereich
+        let eingabe: Vec<i16> = (i16::MIN..=i16::MAX).step_by(7).collect();
+        let mut floats = Vec::new();
+        in_f32(&eingabe, &mut floats);
+        let mut ausgabe = vec![0i16; eingabe.len()];
+        aus_f32(&floats, &mut ausgabe);
+
+        for (a, b) in eingabe.iter().zip(&ausgabe) {
+            assert!((*a as i32 - *b as i32).abs() <= 1, "{a} -> {b}");
+        }
+    }
+
+    #[test]
+    fn vollausschlag_wird_nicht_abgeschnitten() {
+        assert_eq!(i16::MIN.nach_f32(), -1.0);
+        assert!(i16::MAX.nach_f32() < 1.0);
+        assert_eq!(i16::aus_f32(i16::MIN.nach_f32()), i16::MIN);
+        assert_eq!(i16::aus_f32(i16::MAX.nach_f32()), i16::MAX);
+
+        // Ueber Vollausschlag hinaus wird begrenzt statt umzuschlagen
+        assert_eq!(i16::aus_f32(1.0), i16::MAX);
+        assert_eq!(i16::aus_f32(1.5), i16::MAX);
+        assert_eq!(i16::aus_f32(-1.5), i16::MIN);
+    }
+
+    #[test]
+    fn u16_entspricht_verschobenem_i16() {
+        for s in [i16::MIN, -1, 0, 1, i16::MAX] {
+            let u = (s as i32 + 32768) as u16;
+            assert_eq!(u.nach_f32(), s.nach_f32());
+            assert_eq!(u16::aus_f32(s.nach_f32()), u);
+        }
+        assert_eq!(u16::aus_f32(2.0), u16::MAX);
+        assert_eq!(u16::aus_f32(-2.0), 0);
+    }
+
+    #[test]
+    fn in_f32_ersetzt_vorherigen_inhalt() {
+        let mut ziel = vec![0.5; 8];
+        in_f32(&[16384i16, -16384], &mut ziel);
+        assert_eq!(ziel, vec![0.5, -0.5]);
+    }
+}
@@ -0,0 +1,266 @@
+//! Sende-Pipeline ohne Hardware
+//!
+//! Sammelt Samples aus einer [`AudioSource`] zu Frames, schickt sie durch
+//! die DSP-Kette, erkennt Sprache am Pegel, kodiert sie und baut das fertige
+//! [`VoicePacket`] – dieselben Schritte wie die Sende-Schleife im Client.
+//! Takt und Socket bestimmt der Aufrufer; [`SendePipeline::schritt`]
+//! blockiert nie.
+
+use speakeasy_protocol::voice::{
+    AudioCodec, PacketType, VoiceFlags, VoicePacket, VoicePacketHeader,
+};
+
+use crate::codec::{CodecZaehler, SprachEncoder};
+use crate::dsp::vad::rms_energy;
+use crate::error::AudioResult;
+use crate::pipeline::{build_minimal_capture_pipeline, AudioPipeline};
+use crate::quelle::AudioSource;
+
+/// RMS-Pegel, ab dem ein Frame als Sprache gilt (-46 dBFS)
+pub const SPRACH_SCHWELLE: f32 = 0.005;
+
+/// Ergebnis eines [`SendePipeline::schritt`]
+#[derive(Debug)]
+pub enum SendeSchritt {
+    /// Ein Frame ist fertig (Sprache oder Stille)
+    Paket(VoicePacket),
+    /// Die Quelle hat noch keinen vollen Frame geliefert
+    Wartet,
+    /// Die Quelle ist erschoepft und alles ist gesendet
+    Erschoepft,
+}
+
+/// Von der Quelle bis zum Voice-Paket
+pub struct SendePipeline {
+    quelle: Box<dyn AudioSource>,
+    dsp: AudioPipeline,
+    encoder: SprachEncoder,
+    zaehler: CodecZaehler,
+    ssrc: u32,
+    sequenz: u32,
+    spricht: bool,
+    frame: Vec<f32>,
+    gefuellt: usize,
+}
+
+impl SendePipeline {
+    /// Neue Pipeline mit der minimalen Capture-DSP-Kette
+    pub fn neu(quelle: Box<dyn AudioSource>, encoder: SprachEncoder, ssrc: u32) -> Self {
+        let frame = vec![0.0; encoder.frame_size()];
+        Self {
+            quelle,
+            dsp: build_minimal_capture_pipeline(),
+            encoder,
+            zaehler: CodecZaehler::default(),
+            ssrc,
+            sequenz: 0,
+            spricht: false,
+            frame,
+            gefuellt: 0,
+        }
+    }
+
+    /// Ersetzt die DSP-Kette (z.B. [`AudioPipeline::empty`] fuer Musik)
+    pub fn mit_dsp(mut self, dsp: AudioPipeline) -> Self {
+        self.dsp = dsp;
+        self
+    }
+
+    /// Erfasst Codec-Fehler in `zaehler` statt in eigenen Zaehlern
+    pub fn mit_zaehler(mut self, zaehler: CodecZaehler) -> Self {
+        self.zaehler = zaehler;
+        self
+    }
+
+    /// Codec-Fehler dieser Pipeline
+    pub fn zaehler(&self) -> &CodecZaehler {
+        &self.zaehler
+    }
+
+    /// Wird gerade Sprache gesendet?
+    pub fn spricht(&self) -> bool {
+        self.spricht
+    }
+
+    /// Naechste Sequenznummer
+    pub fn sequenz(&self) -> u32 {
+        self.sequenz
+    }
+
+    /// Baut das naechste Paket, sobald ein voller Frame vorliegt
+    ///
+    /// Der letzte, unvollstaendige Frame einer erschoepften Quelle wird mit
+    /// Stille aufgefuellt; Frames haben damit immer die Groesse des
+    /// Encoders. Schlaegt das Kodieren fehl, geht der Frame verloren
+    /// (gezaehlt in [`SendePipeline::zaehler`]); Sequenz und Sprech-Zustand
+    /// bleiben unveraendert.
+    pub fn schritt(&mut self) -> AudioResult<SendeSchritt> {
+        while self.gefuellt < self.frame.len() {
+            let n = self.quelle.read(&mut self.frame[self.gefuellt..]);
+            if n > 0 {
+                self.gefuellt += n;
+                continue;
+            }
+            if !self.quelle.is_exhausted() {
+                return Ok(SendeSchritt::Wartet);
+            }
+            if self.gefuellt == 0 {
+                return Ok(SendeSchritt::Erschoepft);
+            }
+            self.frame[self.gefuellt..].fill(0.0);
+            break;
+        }
+        self.gefuellt = 0;
+
+        let verarbeitet = self.dsp.process_frame(&self.frame);
+        let ist_sprache = rms_energy(&verarbeitet.samples) > SPRACH_SCHWELLE;
+        let nutzdaten = if ist_sprache {
+            let kodiert = self
+                .encoder
+                .encode(&verarbeitet.samples)
+                .inspect_err(|e| self.zaehler.fehler_erfassen(e))?;
+            Some(kodiert)
+        } else {
+            None
+        };
+
+        // Empfaenger erkennen PCMU-Nutzdaten am Flag
+        let mut flags = match self.encoder.codec() {
+            AudioCodec::Opus => 0,
+            AudioCodec::Pcmu => VoiceFlags::PCMU,
+        };
+        if self.encoder.fec_aktiv() {
+            flags |= VoiceFlags::FEC;
+        }
+        if ist_sprache && !self.spricht {
+            flags |= VoiceFlags::SPEAKING_START;
+        } else if !ist_sprache && self.spricht {
+            flags |= VoiceFlags::SPEAKING_STOP;
+        }
+        self.spricht = ist_sprache;
+
+        let seq = self.sequenz;
+        self.sequenz = self.sequenz.wrapping_add(1);
+        let timestamp = seq.wrapping_mul(self.frame.len() as u32);
+
+        let paket = match nutzdaten {
+            Some(payload) => VoicePacket {
+                header: VoicePacketHeader::new(PacketType::Audio, flags, seq, timestamp, self.ssrc),
+                payload,
+            },
+            None => {
+                // Silence-Paket (DTX); das Ende einer Sprechphase markieren
+                let mut paket = VoicePacket::neu_silence(seq, timestamp, self.ssrc);
+                paket.header.flags |= flags & VoiceFlags::SPEAKING_STOP;
+                paket
+            }
+        };
+        Ok(SendeSchritt::Paket(paket))
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::codec::SprachDecoder;
+    use crate::quelle::{PufferQuelle, Stille};
+    use speakeasy_protocol::codec::AudioPreset;
+
+    /// 440-Hz-Sinus bei 48 kHz
+    fn ton(samples: usize) -> Vec<f32> {
+        (0..samples)
+            .map(|i| (i as f32 * 440.0 * std::f32::consts::TAU / 48000.0).sin() * 0.5)
+            .collect()
+    }
+
+    fn pipeline(codec: AudioCodec, samples: Vec<f32>) -> SendePipeline {
+        let encoder = SprachEncoder::new(codec, AudioPreset::Balanced.config()).unwrap();
+        SendePipeline::neu(Box::new(PufferQuelle::neu(samples)), encoder, 0xB07)
+            .mit_dsp(AudioPipeline::empty())
+    }
+
+    fn alle_pakete(pipeline: &mut SendePipeline) -> Vec<VoicePacket> {
+        let mut pakete = Vec::new();
+        loop {
+            match pipeline.schritt().unwrap() {
+                SendeSchritt::Paket(p) => pakete.push(p),
+                SendeSchritt::Erschoepft => return pakete,
+                SendeSchritt::Wartet => panic!("Puffer-Quelle wartet nie"),
+            }
+        }
+    }
+
+    #[test]
+    fn pcmu_ton_ergibt_sprechphase() {
+        // 2,5 Frames Ton, danach ein Frame Stille
+        let mut samples = ton(960 * 5 / 2);
+        samples.extend(vec![0.0; 960]);
+        let mut sender = pipeline(AudioCodec::Pcmu, samples);
+        let pakete = alle_pakete(&mut sender);
+
+        // Der halbe Frame wird aufgefuellt: 3 Sprach- plus 1 Stille-Paket
+        assert_eq!(pakete.len(), 4);
+        assert!(pakete[0].header.hat_flag(VoiceFlags::SPEAKING_START));
+        assert!(pakete[..3].iter().all(|p| {
+            p.header.packet_type == PacketType::Audio
+                && p.header.hat_flag(VoiceFlags::PCMU)
+                && p.payload.len() == 160
+        }));
+        assert_eq!(pakete[3].header.packet_type, PacketType::Silence);
+        assert!(pakete[3].header.hat_flag(VoiceFlags::SPEAKING_STOP));
+        assert!(!sender.spricht());
+
+        let zeitstempel: Vec<u32> = pakete.iter().map(|p| p.header.timestamp).collect();
+        assert_eq!(zeitstempel, [0, 960, 1920, 2880]);
+        assert!(pakete.iter().all(|p| p.header.ssrc == 0xB07));
+
+        // Empfaengerseitig dekodierbar
+        let mut decoder =
+            SprachDecoder::new(AudioCodec::Pcmu, &AudioPreset::Balanced.config()).unwrap();
+        let pcm = decoder.decode(&pakete[1].payload).unwrap();
+        assert!(rms_energy(&pcm) > SPRACH_SCHWELLE);
+    }
+
+    #[test]
+    fn opus_pakete_sind_dekodierbar() {
+        let mut sender = pipeline(AudioCodec::Opus, ton(960 * 3));
+        let pakete = alle_pakete(&mut sender);
+        assert_eq!(pakete.len(), 3);
+        assert!(pakete.iter().all(|p| !p.header.hat_flag(VoiceFlags::PCMU)));
+
+        let mut decoder =
+            SprachDecoder::new(AudioCodec::Opus, &AudioPreset::Balanced.config()).unwrap();
+        for paket in &pakete {
+            let kodiert = paket.encode();
+            let empfangen = VoicePacket::decode(&kodiert).unwrap();
+            assert_eq!(decoder.decode(&empfangen.payload).unwrap().len(), 960);
+        }
+    }
+
+    #[test]
+    fn stille_quelle_sendet_nur_stille_pakete() {
+        let encoder = SprachEncoder::new(AudioCodec::Pcmu, AudioPreset::Balanced.config()).unwrap();
+        let mut sender = SendePipeline::neu(Box::new(Stille), encoder, 1);
+        for seq in 0..5 {
+            let SendeSchritt::Paket(paket) = sender.schritt().unwrap() else {
+                panic!("Stille liefert immer einen Frame");
+            };
+            assert_eq!(paket.header.packet_type, PacketType::Silence);
+            assert_eq!(paket.header.sequence, seq);
+        }
+        assert_eq!(sender.sequenz(), 5);
+    }
+
+    #[test]
+    fn leere_ring_buffer_quelle_wartet() {
+        use ringbuf::{traits::Split, HeapRb};
+        let (_producer, consumer) = HeapRb::<f32>::new(960).split();
+        let encoder = SprachEncoder::new(AudioCodec::Pcmu, AudioPreset::Balanced.config()).unwrap();
+        let mut sender = SendePipeline::neu(Box::new(consumer), encoder, 1);
+        assert!(matches!(sender.schritt().unwrap(), SendeSchritt::Wartet));
+    }
+}
@@ -0,0 +1,654 @@
+//! Stoergeraeusch-Erkennung – Luefter, Tastatur oder Musik im eigenen Mikrofon
+//!
+//! Wer Hintergrundgeraeusche mitsendet, merkt das selbst meist nicht. Die
+//! [`StoergeraeuschErkennung`] bewertet deshalb jeden gesendeten Frame lokal
+//! mit einer leichten Heuristik:
+//!
+//! - **Spektrale Flachheit** (geometrisches / arithmetisches Mittel des
+//!   Leistungsspektrums): breitbandiges Rauschen ist flach, Sprache besteht
+//!   aus Harmonischen und ist es nicht.
+//! - **Energie ausserhalb der Sprachbaender** (300 – 3400 Hz): Bass und
+//!   Hoehen von Musik, Brummen und Zischen von Luefter.
+//! - **Gleichmaessiger Pegel** ueber eine halbe Sekunde: Sprache schwankt im
+//!   Silbentakt, Luefter und Musik laufen durch.
+//!
+//! Ein Frame gilt als Stoergeraeusch, wenn die Sprach-Erkennung (VAD bzw.
+//! PTT) ihn sendet, der Pegel gleichmaessig ist und mindestens eines der
+//! spektralen Merkmale auf Nicht-Sprache deutet – die Heuristik widerspricht
+//! dann also der VAD. Ueber ein gleitendes Fenster ergibt der Anteil solcher
+//! Frames an allen gesendeten den Wert `score`. Liegt er laenger als
+//! `dauer` ueber der Schwelle, meldet [`StoergeraeuschErkennung::verarbeiten`]
+//! eine Warnung, hoechstens einmal pro `abstand`.
+//!
+//! Die Analyse laeuft auf einem auf ca. 16 kHz ausgeduennten Signal mit einer
+//! 256-Punkte-FFT pro Frame; das kostet nur einen Bruchteil des
+//! Frame-Budgets. Es verlaesst kein Audio das Geraet. Wie bei der
+//! [`HardwareStummErkennung`](crate::HardwareStummErkennung) ergibt sich der
+//! Takt aus den verarbeiteten Samples, nicht aus der Uhr.
+
+use std::collections::VecDeque;
+use std::f32::consts::PI;
+use std::time::Duration;
+
+use crate::dsp::vad::rms_energy;
+
+/// Laenge der FFT (Zweierpotenz)
+const FFT_LAENGE: usize = 256;
+
+/// Ziel-Abtastrate der Analyse
+const ANALYSE_RATE: u32 = 16_000;
+
+/// Sprachband fuer den Anteil ausserhalb
+const SPRACHBAND_HZ: (f32, f32) = (300.0, 3400.0);
+
+/// Untere Grenze der Analyse (darunter nur Gleichanteil und Trittschall)
+const UNTERGRENZE_HZ: f32 = 60.0;
+
+/// Flachheit, ab der ein Spektrum als rauschartig gilt
+const FLACHHEIT_SCHWELLE: f32 = 0.25;
+
+/// Anteil ausserhalb des Sprachbands, ab dem ein Frame als Musik/Brummen gilt
+const AUSSERHALB_SCHWELLE: f32 = 0.5;
+
+/// Zeitraum fuer die Pegel-Schwankung
+const PEGEL_FENSTER: Duration = Duration::from_millis(500);
+
+/// Variationskoeffizient des Pegels, unter dem er als gleichmaessig gilt
+const SCHWANKUNG_SCHWELLE: f32 = 0.25;
+
+/// Mindestanteil gesendeter Samples im Fenster fuer eine Bewertung
+const MIN_GESENDET_ANTEIL: f32 = 0.25;
+
+/// Einstellungen der Erkennung
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StoergeraeuschKonfig {
+    /// Gleitendes Fenster fuer den Anteil
+    pub fenster: Duration,
+    /// Anteil (0.0 – 1.0), ab dem gewarnt wird
+    pub schwelle: f32,
+    /// So lange muss der Anteil ueber der Schwelle liegen
+    pub dauer: Duration,
+    /// Mindestabstand zwischen zwei Warnungen
+    pub abstand: Duration,
+}
+
+impl Default for StoergeraeuschKonfig {
+    fn default() -> Self {
+        Self {
+            fenster: Duration::from_secs(5),
+            schwelle: 0.6,
+            dauer: Duration::from_secs(10),
+            abstand: Duration::from_secs(300),
+        }
+    }
+}
+
+/// Merkmale des zuletzt bewerteten Frames
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct FrameMerkmale {
+    /// Spektrale Flachheit (0.0 = rein tonal, 1.0 = weisses Rauschen)
+    pub flachheit: f32,
+    /// Energieanteil ausserhalb des Sprachbands
+    pub ausserhalb: f32,
+    /// Pegel ueber die letzte halbe Sekunde gleichmaessig
+    pub gleichmaessig: bool,
+    /// Frame als Stoergeraeusch gewertet
+    pub stoerung: bool,
+}
+
+/// Bewerteter Frame im gleitenden Fenster
+#[derive(Debug, Clone, Copy)]
+struct Eintrag {
+    samples: u64,
+    gesendet: bool,
+    stoerung: bool,
+}
+
+/// Schaetzt den Anteil mitgesendeter Stoergeraeusche
+#[derive(Debug, Clone)]
+pub struct StoergeraeuschErkennung {
+    konfig: StoergeraeuschKonfig,
+    /// Samples je Analyse-Sample
+    ausduennung: usize,
+    /// Frequenz je FFT-Bin in Hz
+    bin_hz: f32,
+    /// Ausgeduennte Samples der letzten FFT-Laenge (Ringpuffer)
+    analyse: Vec<f32>,
+    analyse_pos: usize,
+    /// Angefangener Block fuer das naechste Analyse-Sample
+    block_summe: f32,
+    block_laenge: usize,
+    fft: Fft,
+    /// Frame-Pegel mit Laenge ueber `PEGEL_FENSTER`
+    pegel: VecDeque<(u64, f32)>,
+    pegel_samples: u64,
+    fenster: VecDeque<Eintrag>,
+    fenster_samples: u64,
+    gesendet_samples: u64,
+    stoer_samples: u64,
+    /// Samples je Zeitspanne der Konfiguration
+    fenster_grenze: u64,
+    pegel_grenze: u64,
+    dauer_grenze: u64,
+    abstand_grenze: u64,
+    /// Samples seit der Anteil die Schwelle ueberschreitet
+    ueber_schwelle: Option<u64>,
+    /// Samples seit der letzten Warnung
+    seit_warnung: Option<u64>,
+    letzte: FrameMerkmale,
+}
+
+impl StoergeraeuschErkennung {
+    /// Erstellt die Erkennung fuer `sample_rate` Hz (Mono)
+    pub fn neu(sample_rate: u32, konfig: StoergeraeuschKonfig) -> Self {
+        let sample_rate = sample_rate.max(1);
+        let ausduennung = (sample_rate / ANALYSE_RATE).max(1) as usize;
+        let analyse_rate = sample_rate as f32 / ausduennung as f32;
+        let samples = |d: Duration| (sample_rate as u128 * d.as_millis() / 1000) as u64;
+        Self {
+            konfig,
+            ausduennung,
+            bin_hz: analyse_rate / FFT_LAENGE as f32,
+            analyse: vec![0.0; FFT_LAENGE],
+            analyse_pos: 0,
+            block_summe: 0.0,
+            block_laenge: 0,
+            fft: Fft::neu(),
+            pegel: VecDeque::new(),
+            pegel_samples: 0,
+            fenster: VecDeque::new(),
+            fenster_samples: 0,
+            gesendet_samples: 0,
+            stoer_samples: 0,
+            fenster_grenze: samples(konfig.fenster).max(1),
+            pegel_grenze: samples(PEGEL_FENSTER).max(1),
+            dauer_grenze: samples(konfig.dauer),
+            abstand_grenze: samples(konfig.abstand),
+            ueber_schwelle: None,
+            seit_warnung: None,
+            letzte: FrameMerkmale::default(),
+        }
+    }
+
+    /// Verarbeitet einen Frame (nach der DSP, vor dem Encoder)
+    ///
+    /// `gesendet` ist die Entscheidung von VAD bzw. PTT fuer diesen Frame.
+    /// Gibt den Anteil zurueck, wenn jetzt gewarnt werden soll.
+    pub fn verarbeiten(&mut self, samples: &[f32], gesendet: bool) -> Option<f32> {
+        if samples.is_empty() {
+            return None;
+        }
+        let laenge = samples.len() as u64;
+        self.ausduennen(samples);
+        let gleichmaessig = self.pegel_merken(laenge, rms_energy(samples));
+
+        let stoerung = if gesendet {
+            let (flachheit, ausserhalb) = self.spektrum_bewerten();
+            let stoerung = gleichmaessig
+                && (flachheit > FLACHHEIT_SCHWELLE || ausserhalb > AUSSERHALB_SCHWELLE);
+            self.letzte = FrameMerkmale {
+                flachheit,
+                ausserhalb,
+                gleichmaessig,
+                stoerung,
+            };
+            stoerung
+        } else {
+            false
+        };
+        self.eintragen(Eintrag {
+            samples: laenge,
+            gesendet,
+            stoerung,
+        });
+
+        self.seit_warnung = self.seit_warnung.map(|s| s.saturating_add(laenge));
+        let anteil = self.anteil();
+        match anteil {
+            Some(a) if a > self.konfig.schwelle => {
+                self.ueber_schwelle = Some(self.ueber_schwelle.map_or(laenge, |s| s + laenge));
+            }
+            _ => self.ueber_schwelle = None,
+        }
+
+        let lange_genug = self.ueber_schwelle.is_some_and(|s| s >= self.dauer_grenze);
+        let abstand_ok = self.seit_warnung.is_none_or(|s| s >= self.abstand_grenze);
+        if lange_genug && abstand_ok {
+            self.seit_warnung = Some(0);
+            return anteil;
+        }
+        None
+    }
+
+    /// Anteil der Stoergeraeusche an den gesendeten Samples im Fenster
+    ///
+    /// `None`, solange im Fenster zu wenig gesendet wurde.
+    pub fn anteil(&self) -> Option<f32> {
+        let gesendet = self.gesendet_samples as f32;
+        if gesendet < self.fenster_grenze as f32 * MIN_GESENDET_ANTEIL {
+            return None;
+        }
+        Some(self.stoer_samples as f32 / gesendet)
+    }
+
+    /// Merkmale des zuletzt gesendeten Frames
+    pub fn merkmale(&self) -> FrameMerkmale {
+        self.letzte
+    }
+
+    /// Vergisst den bisherigen Verlauf (z.B. nach Geraetewechsel)
+    pub fn zuruecksetzen(&mut self) {
+        self.analyse.fill(0.0);
+        self.analyse_pos = 0;
+        self.block_summe = 0.0;
+        self.block_laenge = 0;
+        self.pegel.clear();
+        self.pegel_samples = 0;
+        self.fenster.clear();
+        self.fenster_samples = 0;
+        self.gesendet_samples = 0;
+        self.stoer_samples = 0;
+        self.ueber_schwelle = None;
+        self.seit_warnung = None;
+        self.letzte = FrameMerkmale::default();
+    }
+
+    /// Duennt per Blockmittel auf ca. 16 kHz aus (grober Tiefpass)
+    fn ausduennen(&mut self, samples: &[f32]) {
+        for &s in samples {
+            self.block_summe += s;
+            self.block_laenge += 1;
+            if self.block_laenge == self.ausduennung {
+                self.analyse[self.analyse_pos] = self.block_summe / self.ausduennung as f32;
+                self.analyse_pos = (self.analyse_pos + 1) % FFT_LAENGE;
+                self.block_summe = 0.0;
+                self.block_laenge = 0;
+            }
+        }
+    }
+
+    /// Merkt den Frame-Pegel; gibt zurueck, ob der Pegel gleichmaessig ist
+    fn pegel_merken(&mut self, laenge: u64, rms: f32) -> bool {
+        self.pegel.push_back((laenge, rms));
+        self.pegel_samples += laenge;
+        while self.pegel_samples > self.pegel_grenze {
+            let Some((alt, _)) = self.pegel.front().copied() else {
+                break;
+            };
+            if self.pegel_samples - alt < self.pegel_grenze {
+                break;
+            }
+            self.pegel.pop_front();
+            self.pegel_samples -= alt;
+        }
+        // Erst mit vollem Fenster aussagekraeftig
+        if self.pegel_samples < self.pegel_grenze {
+            return false;
+        }
+
+        let anzahl = self.pegel.len() as f32;
+        let mittel = self.pegel.iter().map(|(_, p)| p).sum::<f32>() / anzahl;
+        if mittel <= f32::EPSILON {
+            return false;
+        }
+        let varianz = self
+            .pegel
+            .iter()
+            .map(|(_, p)| (p - mittel).powi(2))
+            .sum::<f32>()
+            / anzahl;
+        varianz.sqrt() / mittel < SCHWANKUNG_SCHWELLE
+    }
+
+    /// Flachheit und Anteil ausserhalb des Sprachbands der letzten FFT-Laenge
+    fn spektrum_bewerten(&mut self) -> (f32, f32) {
+        // Chronologisch ab der aeltesten Position
+        let (neu, alt) = self.analyse.split_at(self.analyse_pos);
+        let leistung = self.fft.leistung(alt.iter().chain(neu.iter()).copied());
+
+        let bin = |hz: f32| ((hz / self.bin_hz).round() as usize).clamp(1, FFT_LAENGE / 2);
+        let (von, bis) = (bin(UNTERGRENZE_HZ), FFT_LAENGE / 2);
+        let (band_von, band_bis) = (bin(SPRACHBAND_HZ.0), bin(SPRACHBAND_HZ.1));
+
+        let mut gesamt = 0.0f32;
+        let mut band = 0.0f32;
+        let mut log_summe = 0.0f32;
+        for (k, &p) in leistung.iter().enumerate().take(bis).skip(von) {
+            gesamt += p;
+            if (band_von..=band_bis).contains(&k) {
+                band += p;
+            }
+            log_summe += (p + 1e-12).ln();
+        }
+        if gesamt <= 1e-9 {
+            return (0.0, 0.0);
+        }
+        let anzahl = (bis - von) as f32;
+        let flachheit = (log_summe / anzahl).exp() / (gesamt / anzahl);
+        (flachheit.clamp(0.0, 1.0), 1.0 - band / gesamt)
+    }
+
+    fn eintragen(&mut self, eintrag: Eintrag) {
+        self.fenster_samples += eintrag.samples;
+        if eintrag.gesendet {
+            self.gesendet_samples += eintrag.samples;
+        }
+        if eintrag.stoerung {
+            self.stoer_samples += eintrag.samples;
+        }
+        self.fenster.push_back(eintrag);
+
+        while self.fenster_samples > self.fenster_grenze {
+            let Some(alt) = self.fenster.front().copied() else {
+                break;
+            };
+            if self.fenster_samples - alt.samples < self.fenster_grenze {
+                break;
+            }
+            self.fenster.pop_front();
+            self.fenster_samples -= alt.samples;
+            if alt.gesendet {
+                self.gesendet_samples -= alt.samples;
+            }
+            if alt.stoerung {
+                self.stoer_samples -= alt.samples;
+            }
+        }
+    }
+}
+
+/// Radix-2-FFT fester Laenge mit vorberechneten Tabellen
+#[derive(Debug, Clone)]
+struct Fft {
+    fenster: Vec<f32>,
+    cos: Vec<f32>,
+    sin: Vec<f32>,
+    umkehr: Vec<usize>,
+    re: Vec<f32>,
+    im: Vec<f32>,
+}
+
+impl Fft {
+    fn neu() -> Self {
+        let n = FFT_LAENGE;
+        let bits = n.trailing_zeros();
+        Self {
+            // Hann-Fenster gegen Leckeffekte
+            fenster: (0..n)
+                .map(|i| 0.5 - 0.5 * (2.0 * PI * i as f32 / n as f32).cos())
+                .collect(),
+            cos: (0..n / 2)
+                .map(|k| (2.0 * PI * k as f32 / n as f32).cos())
+                .collect(),
+            sin: (0..n / 2)
+                .map(|k| (2.0 * PI * k as f32 / n as f32).sin())
+                .collect(),
+            umkehr: (0..n)
+                .map(|i| i.reverse_bits() >> (usize::BITS - bits))
+                .collect(),
+            re: vec![0.0; n],
+            im: vec![0.0; n],
+        }
+    }
+
+    /// Leistungsspektrum (Bins 0..=n/2) der gefensterten Samples
+    fn leistung(&mut self, samples: impl Iterator<Item = f32>) -> Vec<f32> {
+        let n = FFT_LAENGE;
+        for (i, s) in samples.take(n).enumerate() {
+            let j = self.umkehr[i];
+            self.re[j] = s * self.fenster[i];
+            self.im[j] = 0.0;
+        }
+
+        let mut laenge = 2;
+        while laenge <= n {
+            let halb = laenge / 2;
+            let schritt = n / laenge;
+            for start in (0..n).step_by(laenge) {
+                for k in 0..halb {
+                    let (c, s) = (self.cos[k * schritt], -self.sin[k * schritt]);
+                    let (a, b) = (start + k, start + k + halb);
+                    let tr = self.re[b] * c - self.im[b] * s;
+                    let ti = self.re[b] * s + self.im[b] * c;
+                    self.re[b] = self.re[a] - tr;
+                    self.im[b] = self.im[a] - ti;
+                    self.re[a] += tr;
+                    self.im[a] += ti;
+                }
+            }
+            laenge *= 2;
+        }
+
+        (0..=n / 2)
+            .map(|k| self.re[k] * self.re[k] + self.im[k] * self.im[k])
+            .collect()
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RATE: u32 = 48_000;
+    /// 20 ms bei 48 kHz
+    const FRAME: usize = 960;
+
+    /// Deterministisches Rauschen (xorshift), gleichverteilt in -1..1
+    struct Rauschen(u32);
+
+    impl Rauschen {
+        fn naechstes(&mut self) -> f32 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 17;
+            self.0 ^= self.0 << 5;
+            self.0 as f32 / u32::MAX as f32 * 2.0 - 1.0
+        }
+    }
+
+    /// Synthetische Sprache: Vokale aus Harmonischen mit Formanten,
+    /// Silben von 180 ms und Pausen von 120 ms mit leisem Raumrauschen
+    fn sprache(sekunden: f32) -> Vec<f32> {
+        let vokale = [
+            (700.0, 1200.0, 2600.0),
+            (300.0, 2300.0, 3000.0),
+            (500.0, 900.0, 2400.0),
+        ];
+        let mut rauschen = Rauschen(7);
+        let n = (sekunden * RATE as f32) as usize;
+        (0..n)
+            .map(|i| {
+                let t = i as f32 / RATE as f32;
+                let silbe = (t / 0.3) as usize;
+                let in_silbe = t % 0.3;
+                let raum = 0.002 * rauschen.naechstes();
+                if in_silbe > 0.18 {
+                    return raum;
+                }
+                let (f1, f2, f3) = vokale[silbe % vokale.len()];
+                let f0 = 120.0 + 25.0 * (2.0 * PI * 3.0 * t).sin() + 10.0 * (silbe % 4) as f32;
+                // Silben-Huellkurve (Sinus-Bogen)
+                let huelle = (PI * in_silbe / 0.18).sin();
+                let mut wert = 0.0;
+                for h in 1..=30 {
+                    let f = f0 * h as f32;
+                    if f > 4000.0 {
+                        break;
+                    }
+                    let formant = |fc: f32, breite: f32| 1.0 / (1.0 + ((f - fc) / breite).powi(2));
+                    let amplitude = formant(f1, 90.0)
+                        + 0.7 * formant(f2, 120.0)
+                        + 0.4 * formant(f3, 160.0)
+                        + 0.02;
+                    wert += amplitude * (2.0 * PI * f * t).sin();
+                }
+                0.08 * huelle * wert + raum
+            })
+            .collect()
+    }
+
+    /// Luefter: breitbandiges, leicht tiefpassgefiltertes Rauschen mit Brummen
+    fn luefter(sekunden: f32) -> Vec<f32> {
+        let mut rauschen = Rauschen(42);
+        let mut tief = 0.0f32;
+        let n = (sekunden * RATE as f32) as usize;
+        (0..n)
+            .map(|i| {
+                let t = i as f32 / RATE as f32;
+                tief = 0.6 * tief + 0.4 * rauschen.naechstes();
+                0.15 * tief + 0.01 * (2.0 * PI * 100.0 * t).sin()
+            })
+            .collect()
+    }
+
+    /// Musik: Bass, Akkord und Hoehen, gleichmaessig durchlaufend
+    fn musik(sekunden: f32) -> Vec<f32> {
+        let n = (sekunden * RATE as f32) as usize;
+        let teile = [
+            (55.0, 0.30),
+            (110.0, 0.25),
+            (440.0, 0.08),
+            (554.4, 0.06),
+            (659.3, 0.06),
+            (4400.0, 0.05),
+            (5200.0, 0.04),
+            (6600.0, 0.03),
+        ];
+        (0..n)
+            .map(|i| {
+                let t = i as f32 / RATE as f32;
+                let tremolo = 1.0 + 0.1 * (2.0 * PI * 2.0 * t).sin();
+                tremolo
+                    * teile
+                        .iter()
+                        .map(|(f, a)| a * (2.0 * PI * f * t).sin())
+                        .sum::<f32>()
+            })
+            .collect()
+    }
+
+    /// Verarbeitet das Signal in 20-ms-Frames; gesendet wird ab -46 dBFS
+    fn durchlaufen(erkennung: &mut StoergeraeuschErkennung, signal: &[f32]) -> Vec<f32> {
+        let mut warnungen = Vec::new();
+        for frame in signal.chunks(FRAME) {
+            let gesendet = rms_energy(frame) > 0.005;
+            warnungen.extend(erkennung.verarbeiten(frame, gesendet));
+        }
+        warnungen
+    }
+
+    fn erkennung() -> StoergeraeuschErkennung {
+        StoergeraeuschErkennung::neu(RATE, StoergeraeuschKonfig::default())
+    }
+
+    #[test]
+    fn fft_findet_sinus_im_richtigen_bin() {
+        let mut fft = Fft::neu();
+        // Bin 16 bei 16 kHz und 256 Punkten = 1000 Hz
+        let sinus = (0..FFT_LAENGE).map(|i| (2.0 * PI * 16.0 * i as f32 / FFT_LAENGE as f32).sin());
+        let leistung = fft.leistung(sinus);
+        let maximum = (0..leistung.len())
+            .max_by(|&a, &b| leistung[a].total_cmp(&leistung[b]))
+            .unwrap();
+        assert_eq!(maximum, 16);
+    }
+
+    #[test]
+    fn sprache_loest_keine_warnung_aus() {
+        let mut e = erkennung();
+        assert!(durchlaufen(&mut e, &sprache(30.0)).is_empty());
+        let anteil = e.anteil().expect("genug gesendet");
+        assert!(anteil < 0.2, "Sprache als Stoerung gewertet: {anteil}");
+    }
+
+    #[test]
+    fn luefter_wird_erkannt() {
+        let mut e = erkennung();
+        let warnungen = durchlaufen(&mut e, &luefter(30.0));
+        assert_eq!(warnungen.len(), 1, "genau eine Warnung im Abstand");
+        assert!(warnungen[0] > 0.9, "Anteil: {}", warnungen[0]);
+        let merkmale = e.merkmale();
+        assert!(merkmale.flachheit > FLACHHEIT_SCHWELLE, "{merkmale:?}");
+        assert!(merkmale.gleichmaessig);
+    }
+
+    #[test]
+    fn musik_wird_erkannt() {
+        let mut e = erkennung();
+        let warnungen = durchlaufen(&mut e, &musik(30.0));
+        assert_eq!(warnungen.len(), 1);
+        let merkmale = e.merkmale();
+        assert!(
+            merkmale.flachheit < FLACHHEIT_SCHWELLE,
+            "Musik ist tonal: {merkmale:?}"
+        );
+        assert!(merkmale.ausserhalb > AUSSERHALB_SCHWELLE, "{merkmale:?}");
+    }
+
+    #[test]
+    fn warnung_erst_nach_der_dauer() {
+        let mut e = erkennung();
+        // Fenster (5 s) fuellt sich, dann 10 s ueber der Schwelle
+        assert!(durchlaufen(&mut e, &luefter(10.0)).is_empty());
+        assert_eq!(durchlaufen(&mut e, &luefter(6.0)).len(), 1);
+    }
+
+    #[test]
+    fn warnungen_haben_mindestabstand() {
+        let mut e = StoergeraeuschErkennung::neu(
+            RATE,
+            StoergeraeuschKonfig {
+                abstand: Duration::from_secs(20),
+                ..Default::default()
+            },
+        );
+        let warnungen = durchlaufen(&mut e, &luefter(60.0));
+        // Nach ca. 10 s die erste, dann alle 20 s
+        assert_eq!(warnungen.len(), 3);
+    }
+
+    #[test]
+    fn sprache_unterbricht_die_dauer() {
+        let mut e = erkennung();
+        assert!(durchlaufen(&mut e, &luefter(7.0)).is_empty());
+        // Lange Sprechphase drueckt den Anteil unter die Schwelle
+        assert!(durchlaufen(&mut e, &sprache(8.0)).is_empty());
+        assert!(durchlaufen(&mut e, &luefter(10.0)).is_empty());
+    }
+
+    #[test]
+    fn ungesendete_frames_zaehlen_nicht() {
+        let mut e = erkennung();
+        for frame in luefter(30.0).chunks(FRAME) {
+            assert_eq!(e.verarbeiten(frame, false), None);
+        }
+        assert_eq!(e.anteil(), None);
+    }
+
+    #[test]
+    fn zuruecksetzen_vergisst_verlauf() {
+        let mut e = erkennung();
+        durchlaufen(&mut e, &luefter(8.0));
+        assert!(e.anteil().is_some());
+        e.zuruecksetzen();
+        assert_eq!(e.anteil(), None);
+        assert_eq!(e.merkmale(), FrameMerkmale::default());
+    }
+
+    #[test]
+    fn kostet_weit_unter_fuenf_prozent_des_frame_budgets() {
+        let signal = musik(10.0);
+        let mut e = erkennung();
+        let start = std::time::Instant::now();
+        durchlaufen(&mut e, &signal);
+        let je_frame = start.elapsed() / (signal.len() / FRAME) as u32;
+        // 5 % von 20 ms; auch ohne Optimierung deutlich darunter
+        assert!(
+            je_frame < Duration::from_millis(1),
+            "{je_frame:?} pro Frame"
+        );
+    }
+}
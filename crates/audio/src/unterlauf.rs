@@ -0,0 +1,389 @@
+//! Unterlauf-Erkennung und adaptiver Vorpuffer fuer den Playback
+//!
+//! Laeuft der Playback-Ring-Buffer leer (CPU-Spitze, langsames Decoding),
+//! fuellt [`UnterlaufBehandlung`] die Luecke mit Stille, die vom zuletzt
+//! ausgegebenen Sample kurz ausgeblendet wird, und wartet danach, bis der
+//! Vorpuffer wieder gefuellt ist (mit kurzem Einblenden beim Fortsetzen).
+//!
+//! Haeufen sich Unterlaeufe innerhalb eines Fensters, wird das Vorpuffer-Ziel
+//! schrittweise erhoeht; bleibt der Playback stabil, sinkt es wieder.
+//! Jitter-Buffer-Ziel und Vorpuffer teilen sich ein [`LatenzBudget`], damit
+//! die Gesamtlatenz an einer Stelle begrenzt wird.
+//!
+//! Alle Groessen sind Samples (bei Mehrkanal: interleaved). Zaehler und
+//! Budget sind reine Atomics – der cpal-Callback wartet nie auf einen Lock.
+
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use ringbuf::traits::{Consumer, Observer};
+use tracing::trace;
+
+/// Konfiguration der Unterlauf-Behandlung (alle Werte in Samples)
+#[derive(Debug, Clone)]
+pub struct UnterlaufKonfig {
+    /// Vorpuffer im stabilen Zustand (Start und nach dem Abklingen)
+    pub basis_vorpuffer: usize,
+    /// Schrittweite beim Erhoehen und Absenken des Vorpuffers
+    pub schritt: usize,
+    /// Obergrenze des Vorpuffers (zusaetzlich durch das Budget begrenzt)
+    pub max_vorpuffer: usize,
+    /// Zeitfenster, in dem Unterlaeufe gezaehlt werden
+    pub fenster: u64,
+    /// Unterlaeufe im Fenster, ab denen der Vorpuffer waechst
+    pub schwelle: u32,
+    /// Stabile Zeit ohne Unterlauf, nach der der Vorpuffer einen Schritt sinkt
+    pub stabil: u64,
+    /// Laenge der Aus- bzw. Einblendung
+    pub blende: usize,
+}
+
+impl Default for UnterlaufKonfig {
+    /// Werte fuer 48 kHz Mono: 20ms Basis, 10ms Schritte, hoechstens 120ms,
+    /// 2 Unterlaeufe in 2s erhoehen, 10s Stabilitaet senken, 2ms Blende
+    fn default() -> Self {
+        Self {
+            basis_vorpuffer: 960,
+            schritt: 480,
+            max_vorpuffer: 5760,
+            fenster: 96_000,
+            schwelle: 2,
+            stabil: 480_000,
+            blende: 96,
+        }
+    }
+}
+
+/// Gemeinsames Latenzbudget von Jitter-Buffer und Playback-Vorpuffer
+///
+/// Der Jitter-Buffer meldet sein Ziel per [`jitter_ziel_setzen`]; der
+/// Vorpuffer bekommt hoechstens, was bis [`max_gesamt`] noch frei ist.
+///
+/// [`jitter_ziel_setzen`]: LatenzBudget::jitter_ziel_setzen
+/// [`max_gesamt`]: LatenzBudget::max_gesamt
+#[derive(Debug, Clone)]
+pub struct LatenzBudget(Arc<BudgetInnen>);
+
+#[derive(Debug)]
+struct BudgetInnen {
+    max_gesamt: usize,
+    jitter_ziel: AtomicUsize,
+    vorpuffer_wunsch: AtomicUsize,
+}
+
+impl LatenzBudget {
+    pub fn neu(max_gesamt: usize) -> Self {
+        Self(Arc::new(BudgetInnen {
+            max_gesamt,
+            jitter_ziel: AtomicUsize::new(0),
+            vorpuffer_wunsch: AtomicUsize::new(0),
+        }))
+    }
+
+    /// Hoechste erlaubte Summe aus Jitter-Ziel und Vorpuffer
+    pub fn max_gesamt(&self) -> usize {
+        self.0.max_gesamt
+    }
+
+    /// Setzt das Ziel des Jitter-Buffers (wird auf das Budget begrenzt)
+    pub fn jitter_ziel_setzen(&self, samples: usize) {
+        self.0
+            .jitter_ziel
+            .store(samples.min(self.0.max_gesamt), Ordering::Relaxed);
+    }
+
+    pub fn jitter_ziel(&self) -> usize {
+        self.0.jitter_ziel.load(Ordering::Relaxed)
+    }
+
+    /// Wirksames Vorpuffer-Ziel (Wunsch, begrenzt durch das Restbudget)
+    pub fn vorpuffer_ziel(&self) -> usize {
+        let frei = self.0.max_gesamt - self.jitter_ziel();
+        self.0.vorpuffer_wunsch.load(Ordering::Relaxed).min(frei)
+    }
+
+    /// Gesamtlatenz aus Jitter-Ziel und wirksamem Vorpuffer
+    pub fn gesamt(&self) -> usize {
+        self.jitter_ziel() + self.vorpuffer_ziel()
+    }
+
+    fn vorpuffer_wunsch_setzen(&self, samples: usize) {
+        self.0.vorpuffer_wunsch.store(samples, Ordering::Relaxed);
+    }
+}
+
+impl Default for LatenzBudget {
+    /// 200ms bei 48 kHz Mono
+    fn default() -> Self {
+        Self::neu(9600)
+    }
+}
+
+/// Unterlauf-Zaehler, lock-free zwischen cpal-Callback und Statistik geteilt
+#[derive(Debug, Clone, Default)]
+pub struct UnterlaufZaehler(Arc<ZaehlerInnen>);
+
+#[derive(Debug, Default)]
+struct ZaehlerInnen {
+    unterlaeufe: AtomicU64,
+    fehlende_samples: AtomicU64,
+}
+
+impl UnterlaufZaehler {
+    /// Callbacks, in denen nicht genug Samples vorlagen
+    pub fn unterlaeufe(&self) -> u64 {
+        self.0.unterlaeufe.load(Ordering::Relaxed)
+    }
+
+    /// Summe der durch Stille ersetzten Samples
+    pub fn fehlende_samples(&self) -> u64 {
+        self.0.fehlende_samples.load(Ordering::Relaxed)
+    }
+
+    fn erfassen(&self, fehlend: usize) {
+        self.0.unterlaeufe.fetch_add(1, Ordering::Relaxed);
+        self.0
+            .fehlende_samples
+            .fetch_add(fehlend as u64, Ordering::Relaxed);
+    }
+}
+
+/// Zustand der Unterlauf-Behandlung im Playback-Callback
+///
+/// Gehoert exklusiv dem Callback; nach aussen sichtbar sind nur
+/// [`UnterlaufZaehler`] und [`LatenzBudget`].
+pub struct UnterlaufBehandlung {
+    konfig: UnterlaufKonfig,
+    budget: LatenzBudget,
+    zaehler: UnterlaufZaehler,
+    /// Ausgegebene Samples seit Start (Zeitbasis fuer Fenster und Abklingen)
+    uhr: u64,
+    /// Wartet, bis der Vorpuffer gefuellt ist
+    vorpuffern: bool,
+    /// Verbleibende Samples der Einblendung nach dem Vorpuffern
+    einblenden: usize,
+    /// Zuletzt ausgegebenes Sample (Startwert der Ausblendung)
+    letzter_wert: f32,
+    fenster_start: u64,
+    im_fenster: u32,
+    /// Zeitpunkt des letzten Unterlaufs oder der letzten Anpassung
+    letzte_aenderung: u64,
+    /// Aktueller Vorpuffer-Wunsch
+    vorpuffer: usize,
+}
+
+impl UnterlaufBehandlung {
+    pub fn neu(konfig: UnterlaufKonfig, budget: LatenzBudget, zaehler: UnterlaufZaehler) -> Self {
+        let vorpuffer = konfig.basis_vorpuffer.min(konfig.max_vorpuffer);
+        budget.vorpuffer_wunsch_setzen(vorpuffer);
+        Self {
+            konfig,
+            budget,
+            zaehler,
+            uhr: 0,
+            vorpuffern: true,
+            einblenden: 0,
+            letzter_wert: 0.0,
+            fenster_start: 0,
+            im_fenster: 0,
+            letzte_aenderung: 0,
+            vorpuffer,
+        }
+    }
+
+    /// Fuellt `data` aus `consumer` und behandelt fehlende Samples
+    pub fn fuellen<C>(&mut self, consumer: &mut C, data: &mut [f32])
+    where
+        C: Consumer<Item = f32> + Observer,
+    {
+        self.uhr += data.len() as u64;
+
+        if self.vorpuffern {
+            if consumer.occupied_len() < self.budget.vorpuffer_ziel().max(1) {
+                self.stille(data);
+                self.abklingen();
+                return;
+            }
+            self.vorpuffern = false;
+            self.einblenden = self.konfig.blende;
+        }
+
+        let gelesen = consumer.pop_slice(data);
+        self.einblendung_anwenden(&mut data[..gelesen]);
+        if let Some(&letzter) = data[..gelesen].last() {
+            self.letzter_wert = letzter;
+        }
+
+        if gelesen < data.len() {
+            let fehlend = data.len() - gelesen;
+            self.zaehler.erfassen(fehlend);
+            trace!(fehlend, "Playback-Unterlauf");
+            self.stille(&mut data[gelesen..]);
+            self.unterlauf_registrieren();
+            self.vorpuffern = true;
+        } else {
+            self.abklingen();
+        }
+    }
+
+    /// Aktueller Vorpuffer-Wunsch (vor Begrenzung durch das Budget)
+    pub fn vorpuffer(&self) -> usize {
+        self.vorpuffer
+    }
+
+    /// Stille, die vom zuletzt ausgegebenen Sample linear ausgeblendet wird
+    fn stille(&mut self, data: &mut [f32]) {
+        let blende = self.konfig.blende.max(1);
+        let start = self.letzter_wert;
+        for (i, s) in data.iter_mut().enumerate() {
+            *s = if i < blende {
+                start * (1.0 - (i + 1) as f32 / blende as f32)
+            } else {
+                0.0
+            };
+        }
+        self.letzter_wert = 0.0;
+    }
+
+    fn einblendung_anwenden(&mut self, data: &mut [f32]) {
+        let blende = self.konfig.blende.max(1);
+        for s in data.iter_mut() {
+            if self.einblenden == 0 {
+                break;
+            }
+            let position = blende - self.einblenden;
+            *s *= position as f32 / blende as f32;
+            self.einblenden -= 1;
+        }
+    }
+
+    fn unterlauf_registrieren(&mut self) {
+        if self.uhr - self.fenster_start > self.konfig.fenster {
+            self.fenster_start = self.uhr;
+            self.im_fenster = 0;
+        }
+        self.im_fenster += 1;
+        self.letzte_aenderung = self.uhr;
+
+        if self.im_fenster >= self.konfig.schwelle && self.vorpuffer < self.konfig.max_vorpuffer {
+            self.vorpuffer = (self.vorpuffer + self.konfig.schritt).min(self.konfig.max_vorpuffer);
+            self.budget.vorpuffer_wunsch_setzen(self.vorpuffer);
+            self.fenster_start = self.uhr;
+            self.im_fenster = 0;
+            trace!(vorpuffer = self.vorpuffer, "Vorpuffer erhoeht");
+        }
+    }
+
+    fn abklingen(&mut self) {
+        if self.vorpuffer <= self.konfig.basis_vorpuffer
+            || self.uhr - self.letzte_aenderung < self.konfig.stabil
+        {
+            return;
+        }
+        self.vorpuffer = self
+            .vorpuffer
+            .saturating_sub(self.konfig.schritt)
+            .max(self.konfig.basis_vorpuffer);
+        self.budget.vorpuffer_wunsch_setzen(self.vorpuffer);
+        self.letzte_aenderung = self.uhr;
+        trace!(vorpuffer = self.vorpuffer, "Vorpuffer gesenkt");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ringbuf::traits::{Producer, Split};
+    use ringbuf::HeapRb;
+
+    fn konfig() -> UnterlaufKonfig {
+        UnterlaufKonfig {
+            basis_vorpuffer: 10,
+            schritt: 10,
+            max_vorpuffer: 30,
+            fenster: 1_000,
+            schwelle: 2,
+            stabil: 500,
+            blende: 4,
+        }
+    }
+
+    fn behandlung() -> (UnterlaufBehandlung, LatenzBudget, UnterlaufZaehler) {
+        let budget = LatenzBudget::neu(1_000);
+        let zaehler = UnterlaufZaehler::default();
+        let behandlung = UnterlaufBehandlung::neu(konfig(), budget.clone(), zaehler.clone());
+        (behandlung, budget, zaehler)
+    }
+
+    #[test]
+    fn stockender_producer_wird_mit_ausblendung_aufgefuellt() {
+        let (mut behandlung, _, zaehler) = behandlung();
+        let (mut producer, mut consumer) = HeapRb::<f32>::new(256).split();
+        producer.push_slice(&[0.5; 12]);
+
+        let mut data = [9.0f32; 16];
+        behandlung.fuellen(&mut consumer, &mut data);
+
+        // Einblendung der ersten Samples nach dem Vorpuffern
+        assert_eq!(&data[..4], &[0.0, 0.125, 0.25, 0.375]);
+        assert!(data[4..12].iter().all(|&s| s == 0.5));
+        // Luecke: vom letzten Sample auf 0 ausgeblendet, dann Stille
+        assert_eq!(&data[12..16], &[0.375, 0.25, 0.125, 0.0]);
+        assert_eq!(zaehler.unterlaeufe(), 1);
+        assert_eq!(zaehler.fehlende_samples(), 4);
+
+        // Ohne Nachschub: reine Stille, kein weiterer Unterlauf waehrend des Vorpufferns
+        behandlung.fuellen(&mut consumer, &mut data);
+        assert!(data.iter().all(|&s| s == 0.0));
+        assert_eq!(zaehler.unterlaeufe(), 1);
+    }
+
+    #[test]
+    fn haeufige_unterlaeufe_erhoehen_vorpuffer_und_stabilitaet_senkt_ihn() {
+        let (mut behandlung, budget, zaehler) = behandlung();
+        let (mut producer, mut consumer) = HeapRb::<f32>::new(1_024).split();
+        let mut data = [0.0f32; 20];
+
+        // Producer stockt wiederholt: Vorpuffer fuellen, dann eine Luecke
+        for _ in 0..4 {
+            producer.push_slice(&[0.1; 30]);
+            behandlung.fuellen(&mut consumer, &mut data);
+            behandlung.fuellen(&mut consumer, &mut data);
+        }
+        assert_eq!(zaehler.unterlaeufe(), 4);
+        assert_eq!(behandlung.vorpuffer(), 30);
+        assert_eq!(budget.vorpuffer_ziel(), 30);
+
+        // Stabiler Nachschub: nach je `stabil` Samples ein Schritt zurueck
+        for _ in 0..100 {
+            producer.push_slice(&[0.1; 20]);
+            behandlung.fuellen(&mut consumer, &mut data);
+        }
+        assert_eq!(zaehler.unterlaeufe(), 4);
+        assert_eq!(behandlung.vorpuffer(), 10);
+        assert_eq!(budget.vorpuffer_ziel(), 10);
+    }
+
+    #[test]
+    fn jitter_ziel_begrenzt_den_vorpuffer() {
+        let budget = LatenzBudget::neu(100);
+        let _behandlung = UnterlaufBehandlung::neu(
+            UnterlaufKonfig {
+                basis_vorpuffer: 60,
+                max_vorpuffer: 80,
+                ..konfig()
+            },
+            budget.clone(),
+            UnterlaufZaehler::default(),
+        );
+        assert_eq!(budget.vorpuffer_ziel(), 60);
+
+        budget.jitter_ziel_setzen(70);
+        assert_eq!(budget.vorpuffer_ziel(), 30);
+        assert_eq!(budget.gesamt(), 100);
+
+        budget.jitter_ziel_setzen(500);
+        assert_eq!(budget.jitter_ziel(), 100);
+        assert_eq!(budget.vorpuffer_ziel(), 0);
+    }
+}
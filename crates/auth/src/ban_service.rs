@@ -146,11 +146,23 @@ impl<B: BanRepository + 'static> BanService<B> {
         Ok(self.ban_repo.is_banned(user_id, ip).await?.is_some())
     }
 
+    /// Laedt den aktiven Ban fuer einen Benutzer oder eine IP
+    ///
+    /// Abgelaufene Bans zaehlen nicht, auch wenn der Cleanup sie noch
+    /// nicht entfernt hat.
+    pub async fn aktiver_ban(
+        &self,
+        user_id: Option<Uuid>,
+        ip: Option<&str>,
+    ) -> AuthResult<Option<BanRecord>> {
+        Ok(self.ban_repo.is_banned(user_id, ip).await?)
+    }
+
     /// Prueft und gibt einen AuthError zurueck wenn gebannt
     ///
     /// Nuetzlich beim Login um Bans direkt als Fehler zu behandeln.
     pub async fn ban_pruefen(&self, user_id: Option<Uuid>, ip: Option<&str>) -> AuthResult<()> {
-        match self.ban_repo.is_banned(user_id, ip).await? {
+        match self.aktiver_ban(user_id, ip).await? {
             None => Ok(()),
             Some(ban) => {
                 if ban.user_id.is_some() {
@@ -338,5 +350,35 @@ mod tests {
 
         let service2 = BanService::neu(repo);
         assert!(!service2.ist_gebannt(Some(target_id), None).await.unwrap());
+        assert_eq!(service2.abgelaufene_bereinigen().await.unwrap(), 1);
+    }
+
+    #[tokio::test]
+    async fn aktiver_ban_prueft_benutzer_und_ip_gemeinsam() {
+        let service = test_service();
+        let target_id = Uuid::new_v4();
+
+        service
+            .ip_bannen(
+                None,
+                "10.0.0.7",
+                "Zweitkonto",
+                Some(Duration::from_secs(60)),
+            )
+            .await
+            .unwrap();
+
+        let ban = service
+            .aktiver_ban(Some(target_id), Some("10.0.0.7"))
+            .await
+            .unwrap()
+            .expect("IP-Ban erwartet");
+        assert_eq!(ban.reason, "Zweitkonto");
+        assert!(ban.expires_at.is_some());
+        assert!(service
+            .aktiver_ban(Some(target_id), Some("10.0.0.8"))
+            .await
+            .unwrap()
+            .is_none());
     }
 }
@@ -1,5 +1,6 @@
 //! Fehlertypen fuer den Auth-Service
 
+use speakeasy_core::SpeakeasyError;
 use thiserror::Error;
 
 /// Alle moeglichen Fehler im Auth-Service
@@ -74,3 +75,116 @@ impl AuthError {
 
 /// Result-Alias fuer den Auth-Service
 pub type AuthResult<T> = Result<T, AuthError>;
+
+impl From<AuthError> for SpeakeasyError {
+    fn from(e: AuthError) -> Self {
+        match e {
+            AuthError::UngueltigeAnmeldedaten => Self::Authentifizierung(e.to_string()),
+            AuthError::BenutzerGesperrt => Self::KontoGesperrt,
+            AuthError::BenutzerGebannt(grund) | AuthError::IpGebannt(grund) => Self::Gebannt(grund),
+            AuthError::SessionUngueltig | AuthError::SessionAbgelaufen => Self::SessionAbgelaufen,
+            AuthError::TokenUngueltig | AuthError::TokenAbgelaufen => {
+                Self::TokenUngueltig(e.to_string())
+            }
+            AuthError::TokenScopeFehlend(scope) => Self::ScopeFehlt(scope),
+            AuthError::ZugriffVerweigert(berechtigung) => {
+                Self::ZugriffVerweigert(format!("Berechtigung '{berechtigung}' fehlt"))
+            }
+            AuthError::BenutzernameVergeben(name) => Self::BenutzernameVergeben(name),
+            AuthError::BenutzerNichtGefunden(name) => Self::BenutzerNichtGefunden(name),
+            AuthError::EinladungUngueltig | AuthError::EinladungErschoepft => {
+                Self::EinladungUngueltig(e.to_string())
+            }
+            AuthError::Datenbank(db) => db.into(),
+            AuthError::PasswortHashing(_) | AuthError::Intern(_) => Self::Intern(e.to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use speakeasy_core::FehlerCode;
+
+    /// Ein Beispiel je Variante; der `match` ohne Wildcard bricht den Build,
+    /// sobald eine Variante hinzukommt, die hier fehlt
+    fn alle_varianten() -> Vec<AuthError> {
+        let beispiele = vec![
+            AuthError::PasswortHashing("argon2".into()),
+            AuthError::UngueltigeAnmeldedaten,
+            AuthError::BenutzerGesperrt,
+            AuthError::BenutzerGebannt("Spam".into()),
+            AuthError::IpGebannt("Spam".into()),
+            AuthError::SessionUngueltig,
+            AuthError::SessionAbgelaufen,
+            AuthError::TokenUngueltig,
+            AuthError::TokenAbgelaufen,
+            AuthError::TokenScopeFehlend("admin".into()),
+            AuthError::ZugriffVerweigert("b_server_stop".into()),
+            AuthError::BenutzernameVergeben("alice".into()),
+            AuthError::BenutzerNichtGefunden("bob".into()),
+            AuthError::EinladungUngueltig,
+            AuthError::EinladungErschoepft,
+            AuthError::Datenbank(speakeasy_db::DbError::nicht_gefunden("x")),
+            AuthError::Intern("kaputt".into()),
+        ];
+        for e in &beispiele {
+            match e {
+                AuthError::PasswortHashing(_)
+                | AuthError::UngueltigeAnmeldedaten
+                | AuthError::BenutzerGesperrt
+                | AuthError::BenutzerGebannt(_)
+                | AuthError::IpGebannt(_)
+                | AuthError::SessionUngueltig
+                | AuthError::SessionAbgelaufen
+                | AuthError::TokenUngueltig
+                | AuthError::TokenAbgelaufen
+                | AuthError::TokenScopeFehlend(_)
+                | AuthError::ZugriffVerweigert(_)
+                | AuthError::BenutzernameVergeben(_)
+                | AuthError::BenutzerNichtGefunden(_)
+                | AuthError::EinladungUngueltig
+                | AuthError::EinladungErschoepft
+                | AuthError::Datenbank(_)
+                | AuthError::Intern(_) => {}
+            }
+        }
+        beispiele
+    }
+
+    /// Varianten, die bewusst als interner Fehler gelten
+    fn darf_intern_sein(e: &AuthError) -> bool {
+        matches!(e, AuthError::PasswortHashing(_) | AuthError::Intern(_))
+    }
+
+    #[test]
+    fn jede_variante_hat_einen_fachlichen_code() {
+        for e in alle_varianten() {
+            let erlaubt = darf_intern_sein(&e);
+            let anzeige = e.to_string();
+            let code = SpeakeasyError::from(e).code();
+            assert!(erlaubt || code != FehlerCode::Intern, "{anzeige}");
+        }
+    }
+
+    #[test]
+    fn spezifische_varianten_bleiben_erhalten() {
+        let code = |e: AuthError| SpeakeasyError::from(e).code();
+        assert_eq!(code(AuthError::IpGebannt("x".into())), FehlerCode::Gebannt);
+        assert_eq!(
+            code(AuthError::SessionUngueltig),
+            FehlerCode::SessionUngueltig
+        );
+        assert_eq!(
+            code(AuthError::ZugriffVerweigert("x".into())),
+            FehlerCode::ZugriffVerweigert
+        );
+        // Datenbankfehler behalten ihre eigene Zuordnung
+        assert_eq!(
+            code(AuthError::Datenbank(speakeasy_db::DbError::Eindeutigkeit(
+                "x".into()
+            ))),
+            FehlerCode::Konflikt
+        );
+    }
+}
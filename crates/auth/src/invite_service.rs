@@ -89,38 +89,8 @@ impl<I: InviteRepository, U: UserRepository, G: ServerGroupRepository> InviteSer
         code: &str,
         user_id: Uuid,
     ) -> AuthResult<EinladungRecord> {
-        // Einladung verwenden (DB prueft Gueltigkeit und erhoet Zaehler)
-        let einladung = self
-            .invite_repo
-            .use_invite(code)
-            .await
-            .map_err(|e| match e {
-                DbError::EinladungUngueltig => AuthError::EinladungUngueltig,
-                DbError::EinladungErschoepft => AuthError::EinladungErschoepft,
-                other => AuthError::Datenbank(other),
-            })?
-            .ok_or(AuthError::EinladungUngueltig)?;
-
-        // Gruppe zuweisen wenn vorhanden
-        if let Some(group_id) = einladung.assigned_group_id {
-            match self.group_repo.add_member(group_id, user_id).await {
-                Ok(()) => {
-                    tracing::info!(
-                        user_id = %user_id,
-                        group_id = %group_id,
-                        "Benutzer via Einladung zu Gruppe hinzugefuegt"
-                    );
-                }
-                Err(e) => {
-                    tracing::warn!(
-                        user_id = %user_id,
-                        group_id = %group_id,
-                        fehler = %e,
-                        "Gruppen-Zuweisung via Einladung fehlgeschlagen"
-                    );
-                }
-            }
-        }
+        let einladung = self.einladung_reservieren(code).await?;
+        self.gruppe_zuweisen(&einladung, user_id).await;
 
         tracing::info!(
             user_id = %user_id,
@@ -132,6 +102,54 @@ impl<I: InviteRepository, U: UserRepository, G: ServerGroupRepository> InviteSer
         Ok(einladung)
     }
 
+    /// Verbraucht eine Nutzung, bevor der Benutzer feststeht
+    ///
+    /// Fuer Registrierungen: der Zaehler wird atomar erhoeht, sodass
+    /// parallele Einloesungen das Limit nicht ueberschreiten. Kommt das Konto
+    /// danach nicht zustande, gibt `reservierung_freigeben` die Nutzung zurueck.
+    pub async fn einladung_reservieren(&self, code: &str) -> AuthResult<EinladungRecord> {
+        // DB prueft Gueltigkeit und erhoet Zaehler
+        self.invite_repo
+            .use_invite(code)
+            .await
+            .map_err(|e| match e {
+                DbError::EinladungUngueltig => AuthError::EinladungUngueltig,
+                DbError::EinladungErschoepft => AuthError::EinladungErschoepft,
+                other => AuthError::Datenbank(other),
+            })?
+            .ok_or(AuthError::EinladungUngueltig)
+    }
+
+    /// Gibt eine mit `einladung_reservieren` verbrauchte Nutzung zurueck
+    pub async fn reservierung_freigeben(&self, code: &str) -> AuthResult<()> {
+        self.invite_repo.release_invite(code).await?;
+        Ok(())
+    }
+
+    /// Weist die Gruppe der Einladung zu (Fehler werden nur geloggt)
+    pub async fn gruppe_zuweisen(&self, einladung: &EinladungRecord, user_id: Uuid) {
+        let Some(group_id) = einladung.assigned_group_id else {
+            return;
+        };
+        match self.group_repo.add_member(group_id, user_id).await {
+            Ok(()) => {
+                tracing::info!(
+                    user_id = %user_id,
+                    group_id = %group_id,
+                    "Benutzer via Einladung zu Gruppe hinzugefuegt"
+                );
+            }
+            Err(e) => {
+                tracing::warn!(
+                    user_id = %user_id,
+                    group_id = %group_id,
+                    fehler = %e,
+                    "Gruppen-Zuweisung via Einladung fehlgeschlagen"
+                );
+            }
+        }
+    }
+
     /// Widerruft einen Einladungscode
     pub async fn einladung_widerrufen(&self, einladung_id: Uuid) -> AuthResult<()> {
         let widerrufen = self.invite_repo.revoke(einladung_id).await?;
@@ -166,7 +184,7 @@ impl<I: InviteRepository, U: UserRepository, G: ServerGroupRepository> InviteSer
 }
 
 /// Generiert einen zufaelligen Einladungscode (alphanumerisch, Grossbuchstaben)
-fn invite_code_generieren() -> String {
+pub fn invite_code_generieren() -> String {
     const ZEICHEN: &[u8] = b"ABCDEFGHJKLMNPQRSTUVWXYZ23456789";
     let mut rng = rand::thread_rng();
     let mut bytes = vec![0u8; INVITE_CODE_LAENGE];
@@ -341,6 +359,19 @@ mod tests {
                 }
             }
         }
+        async fn release_invite(&self, code: &str) -> DbResult<bool> {
+            let mut einladungen = self.einladungen.lock().unwrap();
+            match einladungen
+                .iter_mut()
+                .find(|e| e.code == code && e.used_count > 0)
+            {
+                Some(e) => {
+                    e.used_count -= 1;
+                    Ok(true)
+                }
+                None => Ok(false),
+            }
+        }
         async fn revoke(&self, id: Uuid) -> DbResult<bool> {
             let mut einladungen = self.einladungen.lock().unwrap();
             let vorher = einladungen.len();
@@ -420,6 +451,35 @@ mod tests {
         assert!(matches!(ergebnis, Err(AuthError::EinladungErschoepft)));
     }
 
+    #[tokio::test]
+    async fn freigegebene_reservierung_ist_wieder_einloesbar() {
+        let (service, ersteller_id) = test_setup().await;
+
+        let einladung = service
+            .einladung_erstellen(ersteller_id, None, None, 1, None)
+            .await
+            .unwrap();
+
+        service
+            .einladung_reservieren(&einladung.code)
+            .await
+            .unwrap();
+        assert!(matches!(
+            service.einladung_reservieren(&einladung.code).await,
+            Err(AuthError::EinladungErschoepft)
+        ));
+
+        service
+            .reservierung_freigeben(&einladung.code)
+            .await
+            .unwrap();
+        let erneut = service
+            .einladung_reservieren(&einladung.code)
+            .await
+            .unwrap();
+        assert_eq!(erneut.used_count, 1);
+    }
+
     #[test]
     fn invite_code_format() {
         let code = invite_code_generieren();
@@ -22,7 +22,7 @@ pub mod session;
 pub use api_token::{ApiTokenRecord, ApiTokenStore, ErstellterApiToken, NeuesApiToken};
 pub use ban_service::BanService;
 pub use error::{AuthError, AuthResult};
-pub use invite_service::InviteService;
+pub use invite_service::{invite_code_generieren, InviteService};
 pub use password::{passwort_hashen, passwort_verifizieren};
 pub use permission_service::PermissionService;
 pub use service::AuthService;
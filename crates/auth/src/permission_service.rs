@@ -11,6 +11,7 @@ use uuid::Uuid;
 
 use speakeasy_db::{
     models::{BerechtigungsWert, EffektiveBerechtigung, TriState},
+    permissions::BerechtigungsSpur,
     repository::PermissionRepository,
 };
 
@@ -88,6 +89,21 @@ impl<P: PermissionRepository> PermissionService<P> {
         Ok(perms.into_iter().map(|(k, eb)| (k, eb.wert)).collect())
     }
 
+    /// Loest alle Berechtigungen mit Herkunft auf (Diagnose)
+    ///
+    /// Laedt immer frisch aus der Datenbank und umgeht den Cache; fuer
+    /// Pruefungen im normalen Betrieb `berechtigung_pruefen` verwenden.
+    pub async fn berechtigungen_nachverfolgen(
+        &self,
+        user_id: Uuid,
+        channel_id: Uuid,
+    ) -> AuthResult<Vec<BerechtigungsSpur>> {
+        Ok(self
+            .perm_repo
+            .resolve_with_trace(user_id, channel_id)
+            .await?)
+    }
+
     /// Erfordert eine Berechtigung – gibt Fehler wenn nicht erlaubt
     ///
     /// Wirft `AuthError::ZugriffVerweigert` wenn die Berechtigung nicht gesetzt
@@ -258,6 +274,14 @@ mod tests {
                 .cloned()
                 .unwrap_or_default())
         }
+
+        async fn resolve_with_trace(
+            &self,
+            _user_id: Uuid,
+            _channel_id: Uuid,
+        ) -> DbResult<Vec<BerechtigungsSpur>> {
+            Ok(vec![])
+        }
     }
 
     #[tokio::test]
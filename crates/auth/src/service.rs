@@ -137,6 +137,19 @@ impl<U: UserRepository> AuthService<U> {
         Ok(benutzer)
     }
 
+    /// Verbleibende Gueltigkeit einer Session
+    pub async fn session_restlaufzeit(&self, token: &str) -> AuthResult<chrono::Duration> {
+        self.session_store.restlaufzeit(token).await
+    }
+
+    /// Verlaengert die Session eines weiterhin aktiven Benutzers
+    ///
+    /// Wurde der Benutzer inzwischen gesperrt, endet die Session stattdessen.
+    pub async fn session_verlaengern(&self, token: &str) -> AuthResult<Session> {
+        self.session_validieren(token).await?;
+        self.session_store.verlaengern(token).await
+    }
+
     /// Validiert einen API-Token und gibt Benutzer + Scopes zurueck
     pub async fn api_token_validieren(
         &self,
@@ -157,6 +170,22 @@ impl<U: UserRepository> AuthService<U> {
         Ok((benutzer, record.scopes))
     }
 
+    /// Laedt einen aktiven Benutzer anhand des Namens
+    ///
+    /// Fuer Anmeldungen ohne Passwort, deren Identitaet anderweitig belegt
+    /// ist (z.B. Client-Zertifikat am Commander).
+    pub async fn aktiven_benutzer_laden(&self, username: &str) -> AuthResult<BenutzerRecord> {
+        let benutzer = self
+            .user_repo
+            .get_by_name(username)
+            .await?
+            .ok_or_else(|| AuthError::BenutzerNichtGefunden(username.to_string()))?;
+        if !benutzer.is_active {
+            return Err(AuthError::BenutzerGesperrt);
+        }
+        Ok(benutzer)
+    }
+
     /// Aendert das Passwort eines Benutzers
     ///
     /// Erfordert das alte Passwort zur Verifikation.
@@ -202,6 +231,72 @@ impl<U: UserRepository> AuthService<U> {
         Ok(())
     }
 
+    /// Setzt das Passwort eines Benutzers ohne das alte (Administration)
+    ///
+    /// Der Benutzer gilt danach wieder als mit Standardpasswort angemeldet
+    /// (`password_changed = false`); alle bestehenden Sessions enden.
+    pub async fn passwort_setzen(
+        &self,
+        user_id: Uuid,
+        neues_passwort: &str,
+    ) -> AuthResult<BenutzerRecord> {
+        let neuer_hash = passwort_hashen(neues_passwort)?;
+        let benutzer = self
+            .user_repo
+            .update(
+                user_id,
+                BenutzerUpdate {
+                    password_hash: Some(neuer_hash),
+                    password_changed: Some(false),
+                    ..Default::default()
+                },
+            )
+            .await?;
+
+        let anzahl = self.session_store.alle_invalidieren(user_id).await;
+        tracing::info!(
+            user_id = %user_id,
+            invalidierte_sessions = anzahl,
+            "Passwort zurueckgesetzt, Sessions invalidiert"
+        );
+
+        Ok(benutzer)
+    }
+
+    /// Bestaetigt das Passwort eines Benutzers (z.B. vor der Kontoloeschung)
+    pub async fn passwort_bestaetigen(&self, user_id: Uuid, passwort: &str) -> AuthResult<()> {
+        let benutzer = self
+            .user_repo
+            .get_by_id(user_id)
+            .await?
+            .ok_or_else(|| AuthError::BenutzerNichtGefunden(user_id.to_string()))?;
+
+        if !passwort_verifizieren(passwort, &benutzer.password_hash)? {
+            return Err(AuthError::UngueltigeAnmeldedaten);
+        }
+        Ok(())
+    }
+
+    /// Beendet alle Sessions eines Benutzers und widerruft seine API-Tokens
+    ///
+    /// Gibt die Anzahl beendeter Sessions und widerrufener Tokens zurueck.
+    pub async fn zugaenge_beenden(&self, user_id: Uuid) -> (usize, usize) {
+        let sessions = self.session_store.alle_invalidieren(user_id).await;
+        let mut tokens = 0;
+        for token in self.api_token_store.liste_fuer_user(user_id).await {
+            if !token.widerrufen && self.api_token_store.widerrufen(token.id).await.is_ok() {
+                tokens += 1;
+            }
+        }
+        tracing::info!(
+            user_id = %user_id,
+            sessions,
+            tokens,
+            "Alle Zugaenge des Benutzers beendet"
+        );
+        (sessions, tokens)
+    }
+
     /// Erstellt einen neuen API-Token fuer einen Benutzer
     pub async fn api_token_erstellen(
         &self,
@@ -442,4 +537,46 @@ mod tests {
         // Neues Passwort funktioniert
         let (_, _) = service.anmelden("pwuser", "neues_pw").await.unwrap();
     }
+
+    #[tokio::test]
+    async fn passwort_setzen_ohne_altes_passwort() {
+        let service = test_service();
+        let user = service.registrieren("vergessen", "altes_pw").await.unwrap();
+        let (_, session) = service.anmelden("vergessen", "altes_pw").await.unwrap();
+
+        service.passwort_setzen(user.id, "neues_pw").await.unwrap();
+
+        // Bestehende Sessions enden, nur das neue Passwort gilt
+        assert!(service.session_validieren(&session.token).await.is_err());
+        let ergebnis = service.anmelden("vergessen", "altes_pw").await;
+        assert!(matches!(ergebnis, Err(AuthError::UngueltigeAnmeldedaten)));
+        service.anmelden("vergessen", "neues_pw").await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn zugaenge_beenden_nach_passwort_bestaetigung() {
+        let service = test_service();
+        let user = service.registrieren("weg", "passwort").await.unwrap();
+        let (_, session) = service.anmelden("weg", "passwort").await.unwrap();
+        let token = service
+            .api_token_erstellen(user.id, "Bot".into(), vec![], None)
+            .await
+            .unwrap();
+
+        let ergebnis = service.passwort_bestaetigen(user.id, "falsch").await;
+        assert!(matches!(ergebnis, Err(AuthError::UngueltigeAnmeldedaten)));
+        service
+            .passwort_bestaetigen(user.id, "passwort")
+            .await
+            .unwrap();
+
+        assert_eq!(service.zugaenge_beenden(user.id).await, (1, 1));
+        assert!(service.session_validieren(&session.token).await.is_err());
+        assert!(service
+            .api_token_validieren(&token.token_wert)
+            .await
+            .is_err());
+        // Erneuter Aufruf findet nichts mehr
+        assert_eq!(service.zugaenge_beenden(user.id).await, (0, 0));
+    }
 }
@@ -3,6 +3,11 @@
 //! Implementiert kurzlebige Session-Tokens fuer eingeloggte Benutzer.
 //! Sessions werden im Speicher gehalten (in-memory HashMap mit TTL).
 //! Ein Hintergrund-Task bereinigt abgelaufene Sessions automatisch.
+//!
+//! Solange der Client verbunden bleibt, kann er seine Session mit
+//! [`SessionStore::verlaengern`] erneuern (gleitender Ablauf). Eine bereits
+//! abgelaufene Session bleibt abgelaufen, auch wenn der Cleanup-Task sie
+//! noch nicht entfernt hat.
 
 use std::{collections::HashMap, sync::Arc, time::Duration};
 
@@ -98,6 +103,33 @@ impl SessionStore {
         }
     }
 
+    /// Gibt die verbleibende Gueltigkeit einer Session zurueck
+    ///
+    /// Fehler wie bei [`validieren`](Self::validieren).
+    pub async fn restlaufzeit(&self, token: &str) -> AuthResult<chrono::Duration> {
+        let session = self.validieren(token).await?;
+        Ok(session.laeuft_ab_am - Utc::now())
+    }
+
+    /// Verlaengert eine gueltige Session um die volle TTL ab jetzt
+    ///
+    /// Abgelaufene Sessions werden nicht wiederbelebt, auch wenn der
+    /// Cleanup-Task sie noch nicht entfernt hat (`AuthError::SessionAbgelaufen`).
+    /// Hat er sie bereits entfernt, folgt `AuthError::SessionUngueltig`.
+    pub async fn verlaengern(&self, token: &str) -> AuthResult<Session> {
+        let jetzt = Utc::now();
+        let mut sessions = self.sessions.write().await;
+        match sessions.get_mut(token) {
+            None => Err(AuthError::SessionUngueltig),
+            Some(session) if session.laeuft_ab_am <= jetzt => Err(AuthError::SessionAbgelaufen),
+            Some(session) => {
+                session.laeuft_ab_am = jetzt + chrono::Duration::seconds(SESSION_TTL_SEKUNDEN);
+                tracing::debug!(user_id = %session.user_id, "Session verlaengert");
+                Ok(session.clone())
+            }
+        }
+    }
+
     /// Invalidiert (loescht) eine Session anhand des Tokens
     pub async fn invalidieren(&self, token: &str) -> AuthResult<()> {
         let mut sessions = self.sessions.write().await;
@@ -197,6 +229,71 @@ mod tests {
         assert_eq!(store.anzahl_aktive().await, 1);
     }
 
+    /// Verschiebt den Ablauf einer Session, als waere Zeit vergangen
+    async fn ablauf_setzen(store: &SessionStore, token: &str, in_sekunden: i64) {
+        let mut sessions = store.sessions.write().await;
+        let session = sessions.get_mut(token).expect("Session fehlt");
+        session.laeuft_ab_am = Utc::now() + chrono::Duration::seconds(in_sekunden);
+    }
+
+    #[tokio::test]
+    async fn restlaufzeit_und_verlaengern() {
+        let store = SessionStore::neu();
+        let session = store.erstellen(Uuid::new_v4()).await.unwrap();
+        ablauf_setzen(&store, &session.token, 120).await;
+
+        let rest = store.restlaufzeit(&session.token).await.unwrap();
+        assert!(rest <= chrono::Duration::seconds(120));
+        assert!(rest > chrono::Duration::seconds(100));
+
+        store.verlaengern(&session.token).await.unwrap();
+        let rest = store.restlaufzeit(&session.token).await.unwrap();
+        assert!(rest > chrono::Duration::seconds(SESSION_TTL_SEKUNDEN - 60));
+    }
+
+    #[tokio::test]
+    async fn cleanup_zwischen_warnung_und_verlaengerung() {
+        let store = SessionStore::neu();
+        let session = store.erstellen(Uuid::new_v4()).await.unwrap();
+
+        // Warnung ist raus, dann laeuft die Session ab und der Cleanup greift
+        ablauf_setzen(&store, &session.token, -1).await;
+        assert_eq!(store.cleanup_abgelaufene().await, 1);
+
+        let ergebnis = store.verlaengern(&session.token).await;
+        assert!(matches!(ergebnis, Err(AuthError::SessionUngueltig)));
+    }
+
+    #[tokio::test]
+    async fn abgelaufene_session_wird_nicht_wiederbelebt() {
+        let store = SessionStore::neu();
+        let session = store.erstellen(Uuid::new_v4()).await.unwrap();
+
+        // Abgelaufen, aber vom Cleanup noch nicht entfernt
+        ablauf_setzen(&store, &session.token, -1).await;
+        let ergebnis = store.verlaengern(&session.token).await;
+        assert!(matches!(ergebnis, Err(AuthError::SessionAbgelaufen)));
+        assert_eq!(store.cleanup_abgelaufene().await, 1);
+    }
+
+    #[tokio::test]
+    async fn verlaengerung_und_cleanup_gleichzeitig() {
+        let store = SessionStore::neu();
+        let session = store.erstellen(Uuid::new_v4()).await.unwrap();
+        ablauf_setzen(&store, &session.token, 1).await;
+
+        // Egal wer zuerst den Lock bekommt: die verlaengerte Session
+        // ueberlebt den Cleanup, eine entfernte wird nicht verlaengert
+        let (verlaengert, _) = tokio::join!(
+            store.verlaengern(&session.token),
+            store.cleanup_abgelaufene()
+        );
+        let gueltig = store.validieren(&session.token).await;
+        assert_eq!(verlaengert.is_ok(), gueltig.is_ok());
+        assert!(verlaengert.is_ok(), "Session war beim Cleanup noch gueltig");
+        assert_eq!(store.cleanup_abgelaufene().await, 0);
+    }
+
     #[tokio::test]
     async fn token_sind_eindeutig() {
         let store = SessionStore::neu();
@@ -0,0 +1,64 @@
+//! Betritt einen Kanal und spielt eine WAV-Datei in Schleife ab
+//!
+//! ```text
+//! SE_SERVER=127.0.0.1 SE_BOT_BENUTZER=musik SE_BOT_PASSWORT=<passwort> \
+//!     SE_KANAL=<kanal-uuid> cargo run -p speakeasy-bot --example wav_schleife -- musik.wav
+//! ```
+//!
+//! `SE_PORT` (Standard 9987) waehlt den Signaling-Port, `SE_CODEC=pcmu`
+//! erzwingt PCMU. Beenden mit Strg+C.
+
+use speakeasy_bot::{wav_laden, BotResult, BotVoice, PufferQuelle, Steuerverbindung};
+use speakeasy_core::types::ChannelId;
+use speakeasy_protocol::voice::AudioCodec;
+
+fn umgebung(name: &str) -> String {
+    std::env::var(name).unwrap_or_else(|_| panic!("{name} muss gesetzt sein"))
+}
+
+#[tokio::main]
+async fn main() -> BotResult<()> {
+    tracing_subscriber::fmt().with_env_filter("info").init();
+
+    let datei = std::env::args()
+        .nth(1)
+        .expect("Aufruf: wav_schleife <datei.wav>");
+    let host = std::env::var("SE_SERVER").unwrap_or_else(|_| "127.0.0.1".into());
+    let port = std::env::var("SE_PORT")
+        .ok()
+        .and_then(|p| p.parse().ok())
+        .unwrap_or(9987);
+    let kanal = umgebung("SE_KANAL")
+        .parse()
+        .map(ChannelId)
+        .expect("SE_KANAL ist keine UUID");
+    let codec = std::env::var("SE_CODEC")
+        .ok()
+        .and_then(|name| AudioCodec::aus_name(&name))
+        .unwrap_or_default();
+
+    let samples = wav_laden(&datei)?;
+    println!("{datei}: {:.1}s", samples.len() as f32 / 48000.0);
+
+    let mut steuerung = Steuerverbindung::verbinden(&host, port).await?;
+    steuerung
+        .anmelden(&umgebung("SE_BOT_BENUTZER"), &umgebung("SE_BOT_PASSWORT"))
+        .await?;
+    steuerung.kanal_betreten(kanal).await?;
+    let ready = steuerung.voice_init(codec).await?;
+    let voice = BotVoice::verbinden(&ready, steuerung.host())
+        .await?
+        .ohne_dsp();
+
+    let (stopp_tx, stopp_rx) = tokio::sync::watch::channel(false);
+    tokio::spawn(async move {
+        let _ = tokio::signal::ctrl_c().await;
+        let _ = stopp_tx.send(true);
+    });
+
+    let gesendet = voice
+        .abspielen(Box::new(PufferQuelle::schleife(samples)), stopp_rx)
+        .await?;
+    println!("{gesendet} Pakete gesendet");
+    steuerung.abmelden().await
+}
@@ -0,0 +1,29 @@
+//! Fehlertypen der Bot-Bibliothek
+
+use speakeasy_audio::AudioError;
+use speakeasy_core::FehlerCode;
+use thiserror::Error;
+
+/// Alle moeglichen Fehler eines Bots
+#[derive(Debug, Error)]
+pub enum BotFehler {
+    #[error("IO-Fehler: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Server-Fehler ({code}): {meldung}")]
+    Server { code: FehlerCode, meldung: String },
+
+    #[error("Unerwartete Antwort: {0}")]
+    UnerwarteteAntwort(String),
+
+    #[error("Verbindung vom Server getrennt")]
+    Getrennt,
+
+    #[error("Ungueltige Adresse: {0}")]
+    Adresse(String),
+
+    #[error("Audio-Fehler: {0}")]
+    Audio(#[from] AudioError),
+}
+
+pub type BotResult<T> = Result<T, BotFehler>;
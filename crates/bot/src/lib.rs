@@ -0,0 +1,18 @@
+//! speakeasy-bot – Voice-Bots ohne Audio-Hardware
+//!
+//! - [`Steuerverbindung`]: Anmelden, Kanal betreten, Voice-Init
+//! - [`BotVoice`]: sendet eine [`AudioSource`] im 20ms-Takt per UDP
+//!
+//! speakeasy-audio wird ohne Feature `hardware` eingebunden; cpal und
+//! Audio-Geraete kommen nicht in den Build. Quellen sind Dateien
+//! ([`wav_laden`]), Puffer ([`PufferQuelle`]), [`Stille`] oder eigene
+//! [`AudioSource`]-Implementierungen.
+
+pub mod fehler;
+pub mod steuerung;
+pub mod voice;
+
+pub use fehler::{BotFehler, BotResult};
+pub use speakeasy_audio::{wav_laden, AudioSource, PufferQuelle, Stille};
+pub use steuerung::Steuerverbindung;
+pub use voice::{BotVoice, FRAME_DAUER};
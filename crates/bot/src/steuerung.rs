@@ -0,0 +1,275 @@
+//! Control-Verbindung eines Bots
+//!
+//! Schlanke Variante der Client-Verbindung: Begruessen und Anmelden, Kanal
+//! betreten, Voice-Init und Abmelden. Server-Pings werden beim Warten auf
+//! eine Antwort beantwortet, Ereignisse und verspaetete Antworten
+//! uebersprungen.
+
+use futures_util::{SinkExt, StreamExt};
+use speakeasy_core::types::ChannelId;
+use speakeasy_core::FehlerCode;
+use speakeasy_protocol::control::{
+    ChannelJoinRequest, ChannelJoinResponse, ControlMessage, ControlPayload, LoginRequest,
+    LoginResponse, LogoutRequest, VoiceInitRequest, VoiceReadyResponse,
+};
+use speakeasy_protocol::handshake;
+use speakeasy_protocol::voice::AudioCodec;
+use speakeasy_protocol::wire::FrameCodec;
+use tokio::net::TcpStream;
+use tokio_util::codec::Framed;
+
+use crate::fehler::{BotFehler, BotResult};
+
+/// TCP-Control-Verbindung zum Signaling-Server
+pub struct Steuerverbindung {
+    framed: Framed<TcpStream, FrameCodec>,
+    host: String,
+    naechste_id: u32,
+}
+
+impl Steuerverbindung {
+    /// Verbindet sich mit `host:port` (ohne TLS)
+    pub async fn verbinden(host: &str, port: u16) -> BotResult<Self> {
+        let stream = TcpStream::connect((host, port)).await?;
+        stream.set_nodelay(true)?;
+        tracing::debug!(host, port, "Control-Verbindung aufgebaut");
+        Ok(Self {
+            framed: Framed::new(stream, FrameCodec::new()),
+            host: host.to_string(),
+            naechste_id: 1,
+        })
+    }
+
+    /// Host der Control-Verbindung (Rueckfall fuer die Voice-Adresse)
+    pub fn host(&self) -> &str {
+        &self.host
+    }
+
+    /// Meldet den Bot mit Benutzername und Passwort an
+    ///
+    /// Vorher gleicht `Hello` die Protokollversion ab; der Bot braucht nur
+    /// die Faehigkeit `voice`.
+    pub async fn anmelden(&mut self, benutzer: &str, passwort: &str) -> BotResult<LoginResponse> {
+        self.begruessen().await?;
+        let antwort = self
+            .anfrage(ControlPayload::Login(LoginRequest {
+                username: benutzer.to_string(),
+                password: passwort.to_string(),
+                token: None,
+                client_version: env!("CARGO_PKG_VERSION").to_string(),
+                display_name: None,
+                capture_consent: false,
+            }))
+            .await?;
+        match antwort {
+            ControlPayload::LoginResponse(login) => {
+                tracing::info!(user_id = %login.user_id.inner(), "Bot angemeldet");
+                Ok(login)
+            }
+            andere => Err(unerwartet("LoginResponse", &andere)),
+        }
+    }
+
+    /// Versionsabgleich per `Hello`/`Welcome` vor dem Login
+    async fn begruessen(&mut self) -> BotResult<()> {
+        let hello = handshake::hello(concat!("speakeasy-bot/", env!("CARGO_PKG_VERSION")));
+        let welcome = match self.anfrage(ControlPayload::Hello(hello.clone())).await? {
+            ControlPayload::Welcome(welcome) => welcome,
+            andere => return Err(unerwartet("Welcome", &andere)),
+        };
+        let kompatibel = handshake::pruefen(&hello, &welcome, &[handshake::faehigkeit::VOICE])
+            .map_err(|e| BotFehler::Server {
+                code: FehlerCode::ProtokollVersion,
+                meldung: e.to_string(),
+            })?;
+        if !kompatibel.fehlend.is_empty() {
+            return Err(BotFehler::UnerwarteteAntwort(format!(
+                "Server ohne Faehigkeiten {:?}",
+                kompatibel.fehlend
+            )));
+        }
+        tracing::debug!(server = %welcome.server_version, "Server begruesst");
+        Ok(())
+    }
+
+    /// Betritt einen Kanal (sendend, nicht nur zuhoerend)
+    pub async fn kanal_betreten(&mut self, kanal: ChannelId) -> BotResult<ChannelJoinResponse> {
+        let antwort = self
+            .anfrage(ControlPayload::ChannelJoin(ChannelJoinRequest {
+                channel_id: kanal,
+                password: None,
+                listen_only: false,
+            }))
+            .await?;
+        match antwort {
+            ControlPayload::ChannelJoinResponse(beitritt) => {
+                if beitritt.listen_only {
+                    tracing::warn!(kanal = %kanal.inner(), "Bot darf im Kanal nicht senden");
+                }
+                Ok(beitritt)
+            }
+            andere => Err(unerwartet("ChannelJoinResponse", &andere)),
+        }
+    }
+
+    /// Fordert eine Voice-Sitzung an
+    pub async fn voice_init(&mut self, codec: AudioCodec) -> BotResult<VoiceReadyResponse> {
+        let antwort = self
+            .anfrage(ControlPayload::VoiceInit(VoiceInitRequest {
+                client_udp_port: 0,
+                preferred_codec: codec.name().to_string(),
+                dtls_fingerprint: None,
+                force_new: false,
+                resequencing: true,
+                e2e_public_key: None,
+                codec_negotiation: None,
+            }))
+            .await?;
+        match antwort {
+            ControlPayload::VoiceReady(ready) => {
+                tracing::info!(ssrc = ready.ssrc, codec = %ready.codec, "Voice-Sitzung bereit");
+                Ok(ready)
+            }
+            andere => Err(unerwartet("VoiceReady", &andere)),
+        }
+    }
+
+    /// Meldet den Bot ab und schliesst die Verbindung
+    pub async fn abmelden(mut self) -> BotResult<()> {
+        let antwort = self
+            .anfrage(ControlPayload::Logout(LogoutRequest {
+                reason: Some("Bot beendet".into()),
+            }))
+            .await;
+        let _ = self.framed.close().await;
+        antwort.map(|_| ())
+    }
+
+    /// Sendet eine Anfrage und wartet auf die zugehoerige Antwort
+    async fn anfrage(&mut self, payload: ControlPayload) -> BotResult<ControlPayload> {
+        let request_id = self.naechste_id;
+        self.naechste_id = self.naechste_id.wrapping_add(1).max(1);
+        self.framed
+            .send(ControlMessage::new(request_id, payload))
+            .await?;
+
+        loop {
+            let nachricht = self.framed.next().await.ok_or(BotFehler::Getrennt)??;
+            if let ControlPayload::Ping(ref ping) = nachricht.payload {
+                let jetzt = std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .unwrap_or_default()
+                    .as_millis() as u64;
+                self.framed
+                    .send(ControlMessage::pong(
+                        nachricht.request_id,
+                        ping.timestamp_ms,
+                        jetzt,
+                    ))
+                    .await?;
+                continue;
+            }
+            // Ereignisse tragen request_id 0 oder gehoeren zu keiner Anfrage
+            if nachricht.request_id != request_id {
+                continue;
+            }
+            if let ControlPayload::Error(fehler) = nachricht.payload {
+                return Err(BotFehler::Server {
+                    code: fehler.fehler_code(),
+                    meldung: fehler.message,
+                });
+            }
+            return Ok(nachricht.payload);
+        }
+    }
+}
+
+fn unerwartet(erwartet: &str, erhalten: &ControlPayload) -> BotFehler {
+    BotFehler::UnerwarteteAntwort(format!(
+        "Erwartet {erwartet}, erhalten: {:?}",
+        std::mem::discriminant(erhalten)
+    ))
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use speakeasy_core::types::{ServerId, UserId};
+    use speakeasy_protocol::control::ErrorCode;
+    use tokio::net::TcpListener;
+
+    /// Server, der `Hello` begruesst und auf jede andere Anfrage mit `antwort`
+    /// reagiert, vorher aber pingt und ein Ereignis schickt
+    async fn server(antwort: fn(u32) -> ControlMessage) -> u16 {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut framed = Framed::new(stream, FrameCodec::new());
+            while let Some(Ok(anfrage)) = framed.next().await {
+                if matches!(anfrage.payload, ControlPayload::Hello(_)) {
+                    let welcome = ControlPayload::Welcome(handshake::welcome("test"));
+                    framed
+                        .send(ControlMessage::new(anfrage.request_id, welcome))
+                        .await
+                        .unwrap();
+                    continue;
+                }
+                framed.send(ControlMessage::ping(0, 42)).await.unwrap();
+                let Some(Ok(pong)) = framed.next().await else {
+                    return;
+                };
+                assert!(matches!(pong.payload, ControlPayload::Pong(_)));
+                framed
+                    .send(ControlMessage::error(
+                        anfrage.request_id + 100,
+                        ErrorCode::InternalError,
+                        "alt",
+                    ))
+                    .await
+                    .unwrap();
+                framed.send(antwort(anfrage.request_id)).await.unwrap();
+            }
+        });
+        port
+    }
+
+    #[tokio::test]
+    async fn anmelden_beantwortet_pings() {
+        let port = server(|id| {
+            ControlMessage::new(
+                id,
+                ControlPayload::LoginResponse(LoginResponse {
+                    user_id: UserId::new(),
+                    session_token: "t".into(),
+                    server_id: ServerId::new(),
+                    expires_at: 0,
+                    server_groups: Vec::new(),
+                    must_change_password: false,
+                    welcome_message: None,
+                    motd: None,
+                }),
+            )
+        })
+        .await;
+        let mut verbindung = Steuerverbindung::verbinden("127.0.0.1", port)
+            .await
+            .unwrap();
+        let login = verbindung.anmelden("bot", "geheim").await.unwrap();
+        assert_eq!(login.session_token, "t");
+    }
+
+    #[tokio::test]
+    async fn server_fehler_wird_abgebildet() {
+        let port = server(|id| ControlMessage::error(id, ErrorCode::Banned, "gesperrt")).await;
+        let mut verbindung = Steuerverbindung::verbinden("127.0.0.1", port)
+            .await
+            .unwrap();
+        let fehler = verbindung.anmelden("bot", "geheim").await.unwrap_err();
+        assert!(matches!(fehler, BotFehler::Server { ref meldung, .. } if meldung == "gesperrt"));
+    }
+}
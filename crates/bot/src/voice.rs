@@ -0,0 +1,200 @@
+//! Voice-Strom eines Bots
+//!
+//! [`BotVoice`] sendet eine [`AudioSource`] im 20ms-Takt per UDP an den
+//! Voice-Server. Kodiert wird ueber die
+//! [`SendePipeline`](speakeasy_audio::SendePipeline) aus speakeasy-audio,
+//! also genauso wie im Client, nur ohne Mikrofon.
+
+use std::net::SocketAddr;
+use std::time::Duration;
+
+use speakeasy_audio::{AudioPipeline, AudioSource, SendePipeline, SendeSchritt, SprachEncoder};
+use speakeasy_protocol::codec::{AudioPreset, OpusConfig};
+use speakeasy_protocol::control::VoiceReadyResponse;
+use speakeasy_protocol::voice::AudioCodec;
+use tokio::net::UdpSocket;
+use tokio::sync::watch;
+
+use crate::fehler::{BotFehler, BotResult};
+
+/// Abstand zweier Voice-Pakete
+pub const FRAME_DAUER: Duration = Duration::from_millis(20);
+
+/// UDP-Voice-Verbindung eines Bots
+pub struct BotVoice {
+    socket: UdpSocket,
+    server: SocketAddr,
+    ssrc: u32,
+    codec: AudioCodec,
+    opus_config: OpusConfig,
+    dsp: bool,
+}
+
+impl BotVoice {
+    /// Oeffnet den UDP-Socket fuer eine bestaetigte Voice-Sitzung
+    ///
+    /// Meldet der Server keine eigene IP, gilt `host` der Control-Verbindung.
+    pub async fn verbinden(ready: &VoiceReadyResponse, host: &str) -> BotResult<Self> {
+        let ip = if ready.server_ip.is_empty() {
+            host
+        } else {
+            ready.server_ip.as_str()
+        };
+        let server = tokio::net::lookup_host((ip, ready.server_udp_port))
+            .await?
+            .next()
+            .ok_or_else(|| BotFehler::Adresse(format!("{ip}:{}", ready.server_udp_port)))?;
+        let codec = AudioCodec::aus_name(&ready.codec).unwrap_or_default();
+        Self::neu(server, ready.ssrc, codec).await
+    }
+
+    /// Oeffnet den UDP-Socket fuer eine bekannte Server-Adresse
+    pub async fn neu(server: SocketAddr, ssrc: u32, codec: AudioCodec) -> BotResult<Self> {
+        let lokal: SocketAddr = if server.is_ipv4() {
+            ([0, 0, 0, 0], 0).into()
+        } else {
+            (std::net::Ipv6Addr::UNSPECIFIED, 0).into()
+        };
+        let socket = UdpSocket::bind(lokal).await?;
+        tracing::debug!(server = %server, ssrc, codec = codec.name(), "Voice-Socket geoeffnet");
+        Ok(Self {
+            socket,
+            server,
+            ssrc,
+            codec,
+            opus_config: AudioPreset::Balanced.config(),
+            dsp: true,
+        })
+    }
+
+    /// Opus-Konfiguration fuer den Encoder (Standard: `Balanced`)
+    pub fn mit_opus_config(mut self, config: OpusConfig) -> Self {
+        self.opus_config = config;
+        self
+    }
+
+    /// Ohne Noise Gate und AGC senden, z.B. fuer Musik
+    pub fn ohne_dsp(mut self) -> Self {
+        self.dsp = false;
+        self
+    }
+
+    /// Zugewiesene SSRC
+    pub fn ssrc(&self) -> u32 {
+        self.ssrc
+    }
+
+    /// Ausgehandelter Codec
+    pub fn codec(&self) -> AudioCodec {
+        self.codec
+    }
+
+    /// Lokale Adresse des UDP-Sockets
+    pub fn lokale_adresse(&self) -> BotResult<SocketAddr> {
+        Ok(self.socket.local_addr()?)
+    }
+
+    /// Sendet `quelle`, bis sie erschoepft ist oder `stopp` auf `true` geht
+    ///
+    /// Liefert die Quelle gerade keinen vollen Frame, wird der Takt
+    /// ausgelassen. Gibt die Anzahl gesendeter Pakete zurueck.
+    pub async fn abspielen(
+        &self,
+        quelle: Box<dyn AudioSource>,
+        mut stopp: watch::Receiver<bool>,
+    ) -> BotResult<u64> {
+        let encoder = SprachEncoder::new(self.codec, self.opus_config.clone())?;
+        let mut pipeline = SendePipeline::neu(quelle, encoder, self.ssrc);
+        if !self.dsp {
+            pipeline = pipeline.mit_dsp(AudioPipeline::empty());
+        }
+
+        let mut takt = tokio::time::interval(FRAME_DAUER);
+        takt.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Burst);
+        let mut gesendet = 0u64;
+        while !*stopp.borrow() {
+            tokio::select! {
+                _ = takt.tick() => {
+                    match pipeline.schritt()? {
+                        SendeSchritt::Paket(paket) => {
+                            self.socket.send_to(&paket.encode(), self.server).await?;
+                            gesendet += 1;
+                        }
+                        SendeSchritt::Wartet => {}
+                        SendeSchritt::Erschoepft => break,
+                    }
+                }
+                Ok(()) = stopp.changed() => {}
+            }
+        }
+        tracing::debug!(gesendet, "Wiedergabe beendet");
+        Ok(gesendet)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use speakeasy_audio::{PufferQuelle, SprachDecoder};
+    use speakeasy_protocol::voice::{PacketType, VoiceFlags, VoicePacket};
+
+    /// 440-Hz-Sinus bei 48 kHz
+    fn ton(samples: usize) -> Vec<f32> {
+        (0..samples)
+            .map(|i| (i as f32 * 440.0 * std::f32::consts::TAU / 48000.0).sin() * 0.5)
+            .collect()
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn wav_puffer_kommt_beim_server_an() {
+        let server = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let voice = BotVoice::neu(server.local_addr().unwrap(), 0xB07, AudioCodec::Pcmu)
+            .await
+            .unwrap()
+            .ohne_dsp();
+        let (_stopp_tx, stopp_rx) = watch::channel(false);
+
+        let gesendet = voice
+            .abspielen(Box::new(PufferQuelle::neu(ton(960 * 4))), stopp_rx)
+            .await
+            .unwrap();
+        assert_eq!(gesendet, 4);
+
+        let config = AudioPreset::Balanced.config();
+        let mut decoder = SprachDecoder::new(AudioCodec::Pcmu, &config).unwrap();
+        let mut buf = [0u8; 2048];
+        for seq in 0..4 {
+            let (n, von) = server.recv_from(&mut buf).await.unwrap();
+            assert_eq!(von.port(), voice.lokale_adresse().unwrap().port());
+            let paket = VoicePacket::decode(&buf[..n]).unwrap();
+            assert_eq!(paket.header.sequence, seq);
+            assert_eq!(paket.header.ssrc, 0xB07);
+            assert_eq!(paket.header.packet_type, PacketType::Audio);
+            assert_eq!(paket.header.hat_flag(VoiceFlags::SPEAKING_START), seq == 0);
+            assert_eq!(decoder.decode(&paket.payload).unwrap().len(), 960);
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn stopp_beendet_schleife() {
+        let server = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let voice = BotVoice::neu(server.local_addr().unwrap(), 1, AudioCodec::Pcmu)
+            .await
+            .unwrap();
+        let (stopp_tx, stopp_rx) = watch::channel(false);
+
+        let wiedergabe = tokio::spawn(async move {
+            voice
+                .abspielen(Box::new(PufferQuelle::schleife(ton(960))), stopp_rx)
+                .await
+        });
+        tokio::time::sleep(FRAME_DAUER * 10).await;
+        stopp_tx.send(true).unwrap();
+        let gesendet = wiedergabe.await.unwrap().unwrap();
+        assert!((9..=12).contains(&gesendet), "gesendet: {gesendet}");
+    }
+}
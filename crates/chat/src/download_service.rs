@@ -0,0 +1,257 @@
+//! DownloadService – signierte Download-Links fuer Dateianhaenge
+//!
+//! [`DownloadService::link_anfordern`] erzeugt fuer einen angemeldeten
+//! Benutzer einen zeitlich begrenzten Link samt SHA-256 und Groesse, damit
+//! der Client den Download pruefen kann. Der Token traegt Datei, Benutzer
+//! und Ablaufzeit und ist mit HMAC-SHA256 signiert:
+//!
+//! ```text
+//! <file_id>.<user_id>.<ablauf_unix>.<signatur_hex>
+//! ```
+//!
+//! Es gibt keinen Token-Zustand in der Datenbank. Ein Link ist bis zum Ablauf
+//! beliebig oft einloesbar; jede Auslieferung landet im Zugriffsprotokoll
+//! des FileService. Mit zufaelligem Schluessel verfallen alle Links beim
+//! Neustart des Servers.
+
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+
+use chrono::{DateTime, Duration, Utc};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use uuid::Uuid;
+
+use speakeasy_db::{ChatMessageRepository, Datenbank, FileRepository, SqliteDb};
+
+use crate::{
+    error::{ChatError, ChatResult},
+    file_service::FileService,
+    storage::{DiskStorage, StorageBackend},
+    types::DateeiInfo,
+};
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Konfiguration des DownloadService
+#[derive(Debug, Clone)]
+pub struct DownloadKonfig {
+    /// Basis-URL fuer Downloads; der Token wird als letztes Segment angehaengt
+    pub basis_url: String,
+    /// Wie lange ein Link gueltig ist
+    pub gueltigkeit: Duration,
+}
+
+impl Default for DownloadKonfig {
+    fn default() -> Self {
+        Self {
+            basis_url: "http://localhost:9301/files/download".into(),
+            gueltigkeit: Duration::minutes(10),
+        }
+    }
+}
+
+/// Download-Link mit den Angaben zur Pruefung
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DownloadAngebot {
+    pub file_id: Uuid,
+    pub filename: String,
+    pub mime_type: String,
+    pub size_bytes: u64,
+    /// SHA-256 des Inhalts (hex)
+    pub sha256: String,
+    pub download_url: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// Inhalt eines gueltigen Tokens
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Freigabe {
+    file_id: Uuid,
+    user_id: Uuid,
+}
+
+/// DownloadService signiert und prueft Download-Links
+pub struct DownloadService<F, C, S>
+where
+    F: FileRepository,
+    C: ChatMessageRepository,
+    S: StorageBackend,
+{
+    files: Arc<FileService<F, C, S>>,
+    schluessel: [u8; 32],
+    konfig: DownloadKonfig,
+}
+
+impl<F, C, S> DownloadService<F, C, S>
+where
+    F: FileRepository,
+    C: ChatMessageRepository,
+    S: StorageBackend,
+{
+    /// Neuen DownloadService mit festem Schluessel erstellen
+    pub fn neu(
+        files: Arc<FileService<F, C, S>>,
+        schluessel: [u8; 32],
+        konfig: DownloadKonfig,
+    ) -> Arc<Self> {
+        Arc::new(Self {
+            files,
+            schluessel,
+            konfig,
+        })
+    }
+
+    /// Neuen DownloadService mit zufaelligem Schluessel erstellen
+    pub fn mit_zufallsschluessel(
+        files: Arc<FileService<F, C, S>>,
+        konfig: DownloadKonfig,
+    ) -> Arc<Self> {
+        let mut schluessel = [0u8; 32];
+        schluessel[..16].copy_from_slice(Uuid::new_v4().as_bytes());
+        schluessel[16..].copy_from_slice(Uuid::new_v4().as_bytes());
+        Self::neu(files, schluessel, konfig)
+    }
+
+    /// Erstellt einen Download-Link fuer eine vorhandene Datei
+    ///
+    /// Ob der Benutzer den Kanal der Datei sehen darf, prueft der Aufrufer
+    /// vorher (siehe [`kanal_von`](Self::kanal_von)).
+    pub async fn link_anfordern(
+        &self,
+        file_id: Uuid,
+        user_id: Uuid,
+    ) -> ChatResult<DownloadAngebot> {
+        let record = self.files.datei_metadaten(file_id).await?;
+        let expires_at = Utc::now() + self.konfig.gueltigkeit;
+        let token = self.token_erstellen(Freigabe { file_id, user_id }, expires_at);
+
+        tracing::debug!(file_id = %file_id, user_id = %user_id, "Download-Link erstellt");
+        Ok(DownloadAngebot {
+            file_id,
+            filename: record.filename,
+            mime_type: record.mime_type,
+            size_bytes: record.size_bytes.max(0) as u64,
+            sha256: record.checksum,
+            download_url: format!("{}/{}", self.konfig.basis_url.trim_end_matches('/'), token),
+            expires_at,
+        })
+    }
+
+    /// Kanal, in dem die Datei gepostet wurde (fuer die Zugriffspruefung)
+    pub async fn kanal_von(&self, file_id: Uuid) -> ChatResult<Uuid> {
+        Ok(self.files.datei_metadaten(file_id).await?.channel_id)
+    }
+
+    /// Prueft einen Token und gibt die Datei zurueck
+    ///
+    /// Abgelaufene Tokens ergeben [`ChatError::TokenAbgelaufen`], ungueltige
+    /// [`ChatError::TokenUngueltig`], geloeschte Dateien
+    /// [`ChatError::DateiNichtGefunden`].
+    pub async fn ausliefern(&self, token: &str) -> ChatResult<(DateeiInfo, Vec<u8>)> {
+        let freigabe = self.token_pruefen(token, Utc::now())?;
+        self.files
+            .datei_herunterladen(freigabe.file_id, freigabe.user_id)
+            .await
+    }
+
+    fn signatur(&self, nutzdaten: &str) -> HmacSha256 {
+        let mut mac = HmacSha256::new_from_slice(&self.schluessel)
+            .expect("HMAC akzeptiert jede Schluessellaenge");
+        mac.update(nutzdaten.as_bytes());
+        mac
+    }
+
+    fn token_erstellen(&self, freigabe: Freigabe, expires_at: DateTime<Utc>) -> String {
+        let nutzdaten = format!(
+            "{}.{}.{}",
+            freigabe.file_id.simple(),
+            freigabe.user_id.simple(),
+            expires_at.timestamp()
+        );
+        let signatur = self.signatur(&nutzdaten).finalize().into_bytes();
+        let hex: String = signatur.iter().map(|b| format!("{b:02x}")).collect();
+        format!("{nutzdaten}.{hex}")
+    }
+
+    fn token_pruefen(&self, token: &str, jetzt: DateTime<Utc>) -> ChatResult<Freigabe> {
+        let ungueltig = || ChatError::TokenUngueltig("Download-Link ungueltig".into());
+        let (nutzdaten, hex) = token.rsplit_once('.').ok_or_else(ungueltig)?;
+        let signatur = hex_dekodieren(hex).ok_or_else(ungueltig)?;
+        // Vergleich in konstanter Zeit
+        self.signatur(nutzdaten)
+            .verify_slice(&signatur)
+            .map_err(|_| ungueltig())?;
+
+        let mut teile = nutzdaten.split('.');
+        let (Some(file_id), Some(user_id), Some(ablauf), None) =
+            (teile.next(), teile.next(), teile.next(), teile.next())
+        else {
+            return Err(ungueltig());
+        };
+        let file_id = Uuid::parse_str(file_id).map_err(|_| ungueltig())?;
+        let user_id = Uuid::parse_str(user_id).map_err(|_| ungueltig())?;
+        let ablauf: i64 = ablauf.parse().map_err(|_| ungueltig())?;
+        if jetzt.timestamp() >= ablauf {
+            return Err(ChatError::TokenAbgelaufen(
+                "Download-Link abgelaufen, bitte neu anfordern".into(),
+            ));
+        }
+        Ok(Freigabe { file_id, user_id })
+    }
+}
+
+pub(crate) fn hex_dekodieren(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) || !hex.is_ascii() {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).ok())
+        .collect()
+}
+
+/// Rueckgabe der [`DateiDienst`]-Methoden
+pub type DateiFuture<'a, T> = Pin<Box<dyn Future<Output = ChatResult<T>> + Send + 'a>>;
+
+/// Objektsichere Sicht auf den [`DownloadService`]
+///
+/// Fuer Signaling und Datei-Server, die die Repository-Typen nicht kennen.
+/// Wie beim [`KontoDienst`](crate::KontoDienst) nur fuer konkrete Typen
+/// implementiert.
+pub trait DateiDienst: Send + Sync {
+    fn kanal_von(&self, file_id: Uuid) -> DateiFuture<'_, Uuid>;
+
+    fn link_anfordern(&self, file_id: Uuid, user_id: Uuid) -> DateiFuture<'_, DownloadAngebot>;
+
+    fn ausliefern<'a>(&'a self, token: &'a str) -> DateiFuture<'a, (DateeiInfo, Vec<u8>)>;
+}
+
+impl DateiDienst for DownloadService<SqliteDb, SqliteDb, DiskStorage> {
+    fn kanal_von(&self, file_id: Uuid) -> DateiFuture<'_, Uuid> {
+        Box::pin(DownloadService::kanal_von(self, file_id))
+    }
+
+    fn link_anfordern(&self, file_id: Uuid, user_id: Uuid) -> DateiFuture<'_, DownloadAngebot> {
+        Box::pin(DownloadService::link_anfordern(self, file_id, user_id))
+    }
+
+    fn ausliefern<'a>(&'a self, token: &'a str) -> DateiFuture<'a, (DateeiInfo, Vec<u8>)> {
+        Box::pin(DownloadService::ausliefern(self, token))
+    }
+}
+
+impl DateiDienst for DownloadService<Datenbank, Datenbank, DiskStorage> {
+    fn kanal_von(&self, file_id: Uuid) -> DateiFuture<'_, Uuid> {
+        Box::pin(DownloadService::kanal_von(self, file_id))
+    }
+
+    fn link_anfordern(&self, file_id: Uuid, user_id: Uuid) -> DateiFuture<'_, DownloadAngebot> {
+        Box::pin(DownloadService::link_anfordern(self, file_id, user_id))
+    }
+
+    fn ausliefern<'a>(&'a self, token: &'a str) -> DateiFuture<'a, (DateeiInfo, Vec<u8>)> {
+        Box::pin(DownloadService::ausliefern(self, token))
+    }
+}
@@ -1,5 +1,6 @@
 //! Fehlertypen fuer das Chat-Crate
 
+use speakeasy_core::SpeakeasyError;
 use thiserror::Error;
 
 /// Chat-Fehlertypen
@@ -14,18 +15,36 @@ pub enum ChatError {
     #[error("Keine Berechtigung: {0}")]
     KeineBerechtigung(String),
 
+    #[error("Frist fuer Aenderungen abgelaufen ({frist_sek} Sekunden nach dem Senden)")]
+    FristAbgelaufen { frist_sek: u64 },
+
     #[error("Datei zu gross: {size} Bytes (Maximum: {max} Bytes)")]
     DateiZuGross { size: i64, max: i64 },
 
     #[error("Speicherkontingent erschoepft: {used} von {max} Bytes belegt")]
     KontingentErschoepft { used: i64, max: i64 },
 
+    #[error("Dateigroesse weicht ab: {erhalten} statt angekuendigter {erwartet} Bytes")]
+    GroesseAbweichend { erwartet: u64, erhalten: u64 },
+
+    #[error("SHA-256 der Datei stimmt nicht mit der angekuendigten Pruefsumme ueberein")]
+    PruefsummeAbweichend,
+
     #[error("Ungueltige Eingabe: {0}")]
     UngueltigeEingabe(String),
 
     #[error("Speicher-Fehler: {0}")]
     SpeicherFehler(String),
 
+    #[error("Zu viele Anfragen: erneut moeglich in {retry_after_secs} Sekunden")]
+    ZuHaeufig { retry_after_secs: u64 },
+
+    #[error("Token ungueltig: {0}")]
+    TokenUngueltig(String),
+
+    #[error("Token abgelaufen: {0}")]
+    TokenAbgelaufen(String),
+
     #[error("Datenbank-Fehler: {0}")]
     DatenbankFehler(#[from] speakeasy_db::DbError),
 
@@ -37,3 +56,111 @@ pub enum ChatError {
 }
 
 pub type ChatResult<T> = Result<T, ChatError>;
+
+impl From<ChatError> for SpeakeasyError {
+    fn from(e: ChatError) -> Self {
+        match e {
+            ChatError::NachrichtNichtGefunden(id) => Self::NachrichtNichtGefunden(id),
+            ChatError::DateiNichtGefunden(id) => Self::DateiNichtGefunden(id),
+            ChatError::KeineBerechtigung(grund) => Self::ZugriffVerweigert(grund),
+            ChatError::FristAbgelaufen { frist_sek } => Self::FristAbgelaufen { frist_sek },
+            ChatError::DateiZuGross { size, max } => Self::DateiZuGross { groesse: size, max },
+            ChatError::KontingentErschoepft { used, max } => {
+                Self::KontingentErschoepft { belegt: used, max }
+            }
+            ChatError::UngueltigeEingabe(grund) => Self::UngueltigeEingabe(grund),
+            ChatError::GroesseAbweichend { .. } | ChatError::PruefsummeAbweichend => {
+                Self::UngueltigeEingabe(e.to_string())
+            }
+            ChatError::SpeicherFehler(grund) => Self::Speicher(grund),
+            ChatError::ZuHaeufig { retry_after_secs } => Self::RateLimit { retry_after_secs },
+            ChatError::TokenUngueltig(grund) | ChatError::TokenAbgelaufen(grund) => {
+                Self::TokenUngueltig(grund)
+            }
+            ChatError::DatenbankFehler(db) => db.into(),
+            // IO betrifft hier den Dateispeicher
+            ChatError::Io(io) => Self::Speicher(io.to_string()),
+            ChatError::Anyhow(e) => Self::Anyhow(e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use speakeasy_core::FehlerCode;
+
+    /// Ein Beispiel je Variante; der `match` ohne Wildcard bricht den Build,
+    /// sobald eine Variante hinzukommt, die hier fehlt
+    fn alle_varianten() -> Vec<ChatError> {
+        let beispiele = vec![
+            ChatError::NachrichtNichtGefunden("1".into()),
+            ChatError::DateiNichtGefunden("2".into()),
+            ChatError::KeineBerechtigung("fremde Nachricht".into()),
+            ChatError::FristAbgelaufen { frist_sek: 900 },
+            ChatError::DateiZuGross { size: 10, max: 5 },
+            ChatError::KontingentErschoepft { used: 10, max: 5 },
+            ChatError::GroesseAbweichend {
+                erwartet: 10,
+                erhalten: 9,
+            },
+            ChatError::PruefsummeAbweichend,
+            ChatError::UngueltigeEingabe("leer".into()),
+            ChatError::SpeicherFehler("voll".into()),
+            ChatError::ZuHaeufig {
+                retry_after_secs: 60,
+            },
+            ChatError::TokenUngueltig("abgelaufen".into()),
+            ChatError::TokenAbgelaufen("Link".into()),
+            ChatError::DatenbankFehler(speakeasy_db::DbError::nicht_gefunden("x")),
+            ChatError::Io(std::io::Error::other("Platte")),
+            ChatError::Anyhow(anyhow::anyhow!("unerwartet")),
+        ];
+        for e in &beispiele {
+            match e {
+                ChatError::NachrichtNichtGefunden(_)
+                | ChatError::DateiNichtGefunden(_)
+                | ChatError::KeineBerechtigung(_)
+                | ChatError::FristAbgelaufen { .. }
+                | ChatError::DateiZuGross { .. }
+                | ChatError::KontingentErschoepft { .. }
+                | ChatError::GroesseAbweichend { .. }
+                | ChatError::PruefsummeAbweichend
+                | ChatError::UngueltigeEingabe(_)
+                | ChatError::SpeicherFehler(_)
+                | ChatError::ZuHaeufig { .. }
+                | ChatError::TokenUngueltig(_)
+                | ChatError::TokenAbgelaufen(_)
+                | ChatError::DatenbankFehler(_)
+                | ChatError::Io(_)
+                | ChatError::Anyhow(_) => {}
+            }
+        }
+        beispiele
+    }
+
+    #[test]
+    fn jede_variante_hat_einen_fachlichen_code() {
+        for e in alle_varianten() {
+            let erlaubt = matches!(e, ChatError::Anyhow(_));
+            let anzeige = e.to_string();
+            let code = SpeakeasyError::from(e).code();
+            assert!(erlaubt || code != FehlerCode::Intern, "{anzeige}");
+        }
+    }
+
+    #[test]
+    fn spezifische_varianten_bleiben_erhalten() {
+        let fehler = SpeakeasyError::from(ChatError::KontingentErschoepft { used: 9, max: 8 });
+        assert_eq!(fehler.code(), FehlerCode::KontingentErschoepft);
+        assert!(fehler.to_string().contains("9 von 8"));
+        assert_eq!(
+            SpeakeasyError::from(ChatError::NachrichtNichtGefunden("x".into())).code(),
+            FehlerCode::NachrichtNichtGefunden
+        );
+        // Abgelaufene Frist ist kein fehlendes Recht: Clients zeigen sie anders an
+        let fehler = SpeakeasyError::from(ChatError::FristAbgelaufen { frist_sek: 900 });
+        assert_eq!(fehler.code(), FehlerCode::FristAbgelaufen);
+        assert_ne!(fehler.code(), FehlerCode::ZugriffVerweigert);
+    }
+}
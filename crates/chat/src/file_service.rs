@@ -1,19 +1,32 @@
 //! FileService – Datei-Upload, Download und Loeschen mit Quota-Pruefung
+//!
+//! Uploads gehen entweder direkt ([`FileService::datei_hochladen`]) oder in
+//! zwei Schritten: [`FileService::upload_anmelden`] merkt Groesse und
+//! Pruefsumme vor, [`FileService::upload_abschliessen`] prueft den per HTTP
+//! uebertragenen Inhalt dagegen und legt erst dann die Datei an.
 
+use std::collections::HashMap;
 use std::sync::Arc;
 
+use chrono::{DateTime, Utc};
 use sha2::{Digest, Sha256};
 use uuid::Uuid;
 
 use speakeasy_db::{
-    models::{NachrichtenTyp as DbNachrichtenTyp, NeueDatei, NeueNachricht},
+    models::{
+        DateiRecord, NachrichtenTyp as DbNachrichtenTyp, NeueDatei, NeueNachricht, NeuerUpload,
+        UploadRecord,
+    },
     ChatMessageRepository, FileRepository,
 };
 
 use crate::{
     error::{ChatError, ChatResult},
     storage::StorageBackend,
-    types::{ChatNachricht, DateeiInfo, DateiUpload, NachrichtenTyp},
+    types::{
+        ChatNachricht, DateeiInfo, DateiUpload, NachrichtenTyp, UploadAnmeldung, Zwischendatei,
+    },
+    zugriffs_log::ZugriffsLogger,
 };
 
 /// Standard-Gruppen-ID fuer Quota-Tracking wenn keine Gruppe angegeben
@@ -29,6 +42,7 @@ where
     file_repo: Arc<F>,
     chat_repo: Arc<C>,
     storage: Arc<S>,
+    zugriffs_log: Option<Arc<ZugriffsLogger>>,
 }
 
 impl<F, C, S> FileService<F, C, S>
@@ -43,6 +57,22 @@ where
             file_repo,
             chat_repo,
             storage,
+            zugriffs_log: None,
+        })
+    }
+
+    /// Neuen FileService mit Download-Zugriffsprotokoll erstellen
+    pub fn neu_mit_zugriffs_log(
+        file_repo: Arc<F>,
+        chat_repo: Arc<C>,
+        storage: Arc<S>,
+        zugriffs_log: Arc<ZugriffsLogger>,
+    ) -> Arc<Self> {
+        Arc::new(Self {
+            file_repo,
+            chat_repo,
+            storage,
+            zugriffs_log: Some(zugriffs_log),
         })
     }
 
@@ -58,35 +88,14 @@ where
         let group = group_id.unwrap_or(DEFAULT_GROUP);
         let size = upload.data.len() as i64;
 
-        if upload.filename.trim().is_empty() {
-            return Err(ChatError::UngueltigeEingabe(
-                "Dateiname darf nicht leer sein".into(),
-            ));
-        }
+        dateiname_pruefen(&upload.filename)?;
 
-        // Kontingent pruefen
-        let quota = self.file_repo.get_quota(group).await?;
-        if size > quota.max_file_size {
-            return Err(ChatError::DateiZuGross {
-                size,
-                max: quota.max_file_size,
-            });
-        }
-        if quota.current_usage + size > quota.max_total_storage {
-            return Err(ChatError::KontingentErschoepft {
-                used: quota.current_usage,
-                max: quota.max_total_storage,
-            });
-        }
-
-        // SHA-256 berechnen
-        let mut hasher = Sha256::new();
-        hasher.update(&upload.data);
-        let checksum = format!("{:x}", hasher.finalize());
+        self.kontingent_pruefen(group, size).await?;
+        let checksum = sha256_hex(&upload.data);
 
-        // Speicher-Pfad aufbauen: channel_id/file_id_filename
+        // Speicher-Pfad aufbauen: channel_id/file_id (Dateiname nur in der DB)
         let file_id = Uuid::new_v4();
-        let storage_path = format!("{}/{}_{}", upload.channel_id, file_id, upload.filename);
+        let storage_path = speicher_pfad(upload.channel_id, file_id);
 
         // Datei im Storage ablegen
         self.storage.store(&storage_path, &upload.data).await?;
@@ -113,11 +122,194 @@ where
         // Kontingent erhoehen
         self.file_repo.increment_usage(group, size).await?;
 
+        self.datei_posten(datei_record).await
+    }
+
+    /// Upload anmelden; der Inhalt folgt bis `gueltig_bis` per HTTP
+    ///
+    /// Prueft Dateiname, Pruefsummen-Format und Kontingent schon hier, damit
+    /// der Client nicht erst die ganze Datei sendet.
+    pub async fn upload_anmelden(
+        &self,
+        anmeldung: &UploadAnmeldung,
+        gueltig_bis: DateTime<Utc>,
+        group_id: Option<&str>,
+    ) -> ChatResult<UploadRecord> {
+        dateiname_pruefen(&anmeldung.filename)?;
+        let checksum = anmeldung.checksum.as_deref().map(str::to_ascii_lowercase);
+        if let Some(hex) = &checksum {
+            if hex.len() != 64 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+                return Err(ChatError::UngueltigeEingabe(
+                    "Pruefsumme muss ein SHA-256 in Hex-Schreibweise sein".into(),
+                ));
+            }
+        }
+        let size = i64::try_from(anmeldung.size_bytes).unwrap_or(i64::MAX);
+        self.kontingent_pruefen(group_id.unwrap_or(DEFAULT_GROUP), size)
+            .await?;
+
+        let upload = self
+            .file_repo
+            .create_upload(NeuerUpload {
+                channel_id: anmeldung.channel_id,
+                uploader_id: anmeldung.uploader_id,
+                filename: &anmeldung.filename,
+                mime_type: &anmeldung.mime_type,
+                size_bytes: size,
+                checksum: checksum.as_deref(),
+                expires_at: gueltig_bis,
+            })
+            .await?;
+
+        tracing::debug!(
+            file_id = %upload.id,
+            size = size,
+            "Datei-Upload angemeldet"
+        );
+        Ok(upload)
+    }
+
+    /// Angemeldeten Upload mit dem uebertragenen Inhalt abschliessen
+    ///
+    /// Prueft Ablauf, Groesse, Kontingent und die angekuendigte Pruefsumme,
+    /// speichert die Datei unter der Upload-ID und postet sie im Kanal.
+    /// Ein bereits abgeschlossener Upload ergibt
+    /// [`ChatError::DateiNichtGefunden`].
+    pub async fn upload_abschliessen(
+        &self,
+        file_id: Uuid,
+        uploader_id: Uuid,
+        data: &[u8],
+        group_id: Option<&str>,
+    ) -> ChatResult<(DateeiInfo, ChatNachricht)> {
+        self.upload_uebernehmen(file_id, uploader_id, Inhalt::Puffer(data), group_id)
+            .await
+    }
+
+    /// Angemeldeten Upload aus einer beim Empfang geschriebenen Zwischendatei
+    /// abschliessen
+    ///
+    /// Wie [`Self::upload_abschliessen`], Groesse und Pruefsumme stammen aus
+    /// der Zwischendatei. Die Datei wird in den Storage verschoben und nicht
+    /// erneut in den Speicher gelesen.
+    pub async fn upload_abschliessen_aus_datei(
+        &self,
+        file_id: Uuid,
+        uploader_id: Uuid,
+        datei: &Zwischendatei,
+        group_id: Option<&str>,
+    ) -> ChatResult<(DateeiInfo, ChatNachricht)> {
+        self.upload_uebernehmen(file_id, uploader_id, Inhalt::Datei(datei), group_id)
+            .await
+    }
+
+    async fn upload_uebernehmen(
+        &self,
+        file_id: Uuid,
+        uploader_id: Uuid,
+        inhalt: Inhalt<'_>,
+        group_id: Option<&str>,
+    ) -> ChatResult<(DateeiInfo, ChatNachricht)> {
+        let group = group_id.unwrap_or(DEFAULT_GROUP);
+        let upload = self
+            .file_repo
+            .get_upload(file_id)
+            .await?
+            .ok_or_else(|| ChatError::DateiNichtGefunden(file_id.to_string()))?;
+
+        if upload.uploader_id != uploader_id {
+            return Err(ChatError::KeineBerechtigung(
+                "Upload gehoert einem anderen Benutzer".into(),
+            ));
+        }
+        if upload.expires_at <= Utc::now() {
+            return Err(ChatError::TokenAbgelaufen(
+                "Upload-Link abgelaufen, bitte neu anfordern".into(),
+            ));
+        }
+        let erhalten = inhalt.groesse();
+        let size = erhalten as i64;
+        if size != upload.size_bytes {
+            return Err(ChatError::GroesseAbweichend {
+                erwartet: upload.size_bytes.max(0) as u64,
+                erhalten,
+            });
+        }
+        // Seit der Anmeldung koennen andere Uploads das Kontingent belegt haben
+        self.kontingent_pruefen(group, size).await?;
+
+        let checksum = inhalt.pruefsumme();
+        if upload
+            .checksum
+            .as_deref()
+            .is_some_and(|erwartet| !erwartet.eq_ignore_ascii_case(&checksum))
+        {
+            return Err(ChatError::PruefsummeAbweichend);
+        }
+
+        let storage_path = speicher_pfad(upload.channel_id, file_id);
+        match inhalt {
+            Inhalt::Puffer(data) => self.storage.store(&storage_path, data).await?,
+            Inhalt::Datei(datei) => self.storage.store_file(&storage_path, &datei.pfad).await?,
+        }
+
+        let datei_record = match self
+            .file_repo
+            .complete_upload(file_id, &storage_path, &checksum)
+            .await
+        {
+            Ok(Some(record)) => record,
+            // Parallel abgeschlossen: die Datei unter demselben Pfad gehoert
+            // dem anderen Abschluss und bleibt liegen
+            Ok(None) => return Err(ChatError::DateiNichtGefunden(file_id.to_string())),
+            Err(e) => {
+                if let Err(fehler) = self.storage.delete(&storage_path).await {
+                    tracing::warn!(%fehler, path = %storage_path, "Storage-Datei konnte nicht entfernt werden");
+                }
+                return Err(e.into());
+            }
+        };
+
+        self.file_repo.increment_usage(group, size).await?;
+
+        self.datei_posten(datei_record).await
+    }
+
+    /// Abgelaufene Upload-Anmeldungen entfernen
+    pub async fn uploads_aufraeumen(&self) -> ChatResult<u64> {
+        Ok(self.file_repo.purge_uploads_before(Utc::now()).await?)
+    }
+
+    /// Prueft Einzel- und Gesamtgrenze des Kontingents fuer `size` Bytes
+    async fn kontingent_pruefen(&self, group: &str, size: i64) -> ChatResult<()> {
+        let quota = self.file_repo.get_quota(group).await?;
+        if size > quota.max_file_size {
+            return Err(ChatError::DateiZuGross {
+                size,
+                max: quota.max_file_size,
+            });
+        }
+        if quota.current_usage + size > quota.max_total_storage {
+            return Err(ChatError::KontingentErschoepft {
+                used: quota.current_usage,
+                max: quota.max_total_storage,
+            });
+        }
+        Ok(())
+    }
+
+    /// Legt die Chat-Nachricht vom Typ 'file' zu einer gespeicherten Datei an
+    async fn datei_posten(
+        &self,
+        datei_record: DateiRecord,
+    ) -> ChatResult<(DateeiInfo, ChatNachricht)> {
+        let size = datei_record.size_bytes;
         let datei_info = DateeiInfo {
             id: datei_record.id,
             filename: datei_record.filename.clone(),
             mime_type: datei_record.mime_type.clone(),
             size_bytes: datei_record.size_bytes,
+            zugriffe: 0,
         };
 
         // Chat-Nachricht vom Typ 'file' erstellen
@@ -125,8 +317,8 @@ where
         let nachricht_record = self
             .chat_repo
             .create(NeueNachricht {
-                channel_id: upload.channel_id,
-                sender_id: upload.uploader_id,
+                channel_id: datei_record.channel_id,
+                sender_id: datei_record.uploader_id,
                 content: &nachricht_content,
                 message_type: DbNachrichtenTyp::File,
                 reply_to: None,
@@ -155,10 +347,8 @@ where
         Ok((datei_info, nachricht))
     }
 
-    /// Datei herunterladen
-    ///
-    /// Gibt Datei-Metadaten und Rohdaten zurueck.
-    pub async fn datei_herunterladen(&self, file_id: Uuid) -> ChatResult<(DateeiInfo, Vec<u8>)> {
+    /// Metadaten einer vorhandenen (nicht geloeschten) Datei
+    pub async fn datei_metadaten(&self, file_id: Uuid) -> ChatResult<DateiRecord> {
         let record = self
             .file_repo
             .get_by_id(file_id)
@@ -168,14 +358,36 @@ where
         if record.deleted_at.is_some() {
             return Err(ChatError::DateiNichtGefunden(file_id.to_string()));
         }
+        Ok(record)
+    }
 
-        let data = self.storage.retrieve(&record.storage_path).await?;
+    /// Datei herunterladen
+    ///
+    /// Gibt Datei-Metadaten und Rohdaten zurueck. Der Zugriff wird, sofern
+    /// konfiguriert, im Zugriffsprotokoll vermerkt (nicht-blockierend).
+    pub async fn datei_herunterladen(
+        &self,
+        file_id: Uuid,
+        user_id: Uuid,
+    ) -> ChatResult<(DateeiInfo, Vec<u8>)> {
+        let record = self.datei_metadaten(file_id).await?;
+
+        let data = match self.storage.retrieve(&record.storage_path).await {
+            Ok(data) => data,
+            Err(e) => {
+                self.zugriff_protokollieren(file_id, user_id, 0, false);
+                return Err(e);
+            }
+        };
+
+        self.zugriff_protokollieren(file_id, user_id, data.len() as i64, true);
 
         let info = DateeiInfo {
             id: record.id,
             filename: record.filename,
             mime_type: record.mime_type,
             size_bytes: record.size_bytes,
+            zugriffe: 0,
         };
 
         Ok((info, data))
@@ -228,12 +440,22 @@ where
     }
 
     /// Alle aktiven Dateien eines Kanals auflisten
+    ///
+    /// Enthaelt die Anzahl protokollierter Downloads je Datei.
     pub async fn dateien_auflisten(&self, channel_id: Uuid) -> ChatResult<Vec<DateeiInfo>> {
         let records = self.file_repo.list_by_channel(channel_id).await?;
+        let ids: Vec<Uuid> = records.iter().map(|r| r.id).collect();
+        let zugriffe: HashMap<Uuid, i64> = self
+            .file_repo
+            .count_access_by_file(&ids)
+            .await?
+            .into_iter()
+            .collect();
 
         Ok(records
             .into_iter()
             .map(|r| DateeiInfo {
+                zugriffe: zugriffe.get(&r.id).copied().unwrap_or(0),
                 id: r.id,
                 filename: r.filename,
                 mime_type: r.mime_type,
@@ -241,4 +463,68 @@ where
             })
             .collect())
     }
+
+    /// Download an das Zugriffsprotokoll uebergeben (falls aktiv)
+    fn zugriff_protokollieren(&self, file_id: Uuid, user_id: Uuid, bytes: i64, completed: bool) {
+        if let Some(log) = &self.zugriffs_log {
+            log.protokollieren(file_id, user_id, bytes, completed);
+        }
+    }
+}
+
+/// Prueft einen vom Client gelieferten Dateinamen
+///
+/// Der Name landet nur in der Datenbank und im Chat, nie im Speicherpfad;
+/// trotzdem werden Pfadtrenner, `..`, absolute Pfade und Steuerzeichen
+/// abgelehnt, damit er auch beim Herunterladen gefahrlos bleibt.
+fn dateiname_pruefen(name: &str) -> ChatResult<()> {
+    if name.trim().is_empty() {
+        return Err(ChatError::UngueltigeEingabe(
+            "Dateiname darf nicht leer sein".into(),
+        ));
+    }
+    if name.contains(['/', '\\'])
+        || name.contains("..")
+        || std::path::Path::new(name).is_absolute()
+        || name.chars().any(char::is_control)
+    {
+        return Err(ChatError::UngueltigeEingabe(
+            "Dateiname darf keine Pfadangaben oder Steuerzeichen enthalten".into(),
+        ));
+    }
+    Ok(())
+}
+
+/// Speicherpfad einer Datei: `<channel_id>/<file_id>`
+fn speicher_pfad(channel_id: Uuid, file_id: Uuid) -> String {
+    format!("{}/{}", channel_id, file_id)
+}
+
+/// SHA-256 eines Inhalts (hex, Kleinbuchstaben)
+/// Inhalt eines abzuschliessenden Uploads
+enum Inhalt<'a> {
+    Puffer(&'a [u8]),
+    Datei(&'a Zwischendatei),
+}
+
+impl Inhalt<'_> {
+    fn groesse(&self) -> u64 {
+        match self {
+            Inhalt::Puffer(data) => data.len() as u64,
+            Inhalt::Datei(datei) => datei.size_bytes,
+        }
+    }
+
+    fn pruefsumme(&self) -> String {
+        match self {
+            Inhalt::Puffer(data) => sha256_hex(data),
+            Inhalt::Datei(datei) => datei.sha256.to_ascii_lowercase(),
+        }
+    }
+}
+
+fn sha256_hex(data: &[u8]) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(data);
+    format!("{:x}", hasher.finalize())
 }
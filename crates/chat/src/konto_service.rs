@@ -0,0 +1,458 @@
+//! KontoService – Datenexport und Loeschung ganzer Benutzerkonten
+//!
+//! Ein Export laeuft in zwei Schritten: [`KontoService::export_anfordern`]
+//! legt den Export an (hoechstens einer pro Sperrfrist) und erzeugt den
+//! Download-Token, [`KontoService::export_erstellen`] schreibt danach das
+//! JSON-Archiv in den Dateispeicher. Der Token wird nur als SHA-256 gespeichert
+//! und ist genau einmal einloesbar; das Archiv wird beim Abholen entfernt.
+//!
+//! Die Inhaltsbereinigung (nach einem Spam-Bann) loescht Nachrichten und
+//! Dateien eines Benutzers aus einem Zeitfenster stapelweise; der Auftrag
+//! haelt den Fortschritt, ein abgebrochener Lauf wird mit derselben ID
+//! fortgesetzt.
+
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+use speakeasy_db::{
+    models::{
+        InhaltsBereinigungRecord, InhaltsStapel, KontoDaten, KontoLoeschAuftrag, KontoLoeschung,
+        NachrichtenRichtlinie, NeueInhaltsBereinigung, NeuerKontoExport,
+    },
+    Datenbank, DbError, KontoRepository, SqliteDb,
+};
+
+use crate::{
+    error::{ChatError, ChatResult},
+    storage::{DiskStorage, StorageBackend},
+};
+
+/// Kennung des Archivformats
+pub const EXPORT_FORMAT: &str = "speakeasy-konto-export/1";
+
+/// Kontingent-Gruppe, der hochgeladene Dateien angerechnet werden
+const DEFAULT_GROUP: &str = "default";
+
+/// Konfiguration des KontoService
+#[derive(Debug, Clone)]
+pub struct KontoKonfig {
+    /// Basis-URL fuer Downloads; der Token wird als letztes Segment angehaengt
+    pub download_basis_url: String,
+    /// Umgang mit den Nachrichten geloeschter Konten
+    pub nachrichten_richtlinie: NachrichtenRichtlinie,
+    /// Wie lange ein Archiv abgeholt werden kann
+    pub export_gueltigkeit: Duration,
+    /// Mindestabstand zwischen zwei selbst angeforderten Exporten
+    pub export_sperrfrist: Duration,
+}
+
+impl Default for KontoKonfig {
+    fn default() -> Self {
+        Self {
+            download_basis_url: "http://localhost:9301/files/export".into(),
+            nachrichten_richtlinie: NachrichtenRichtlinie::default(),
+            export_gueltigkeit: Duration::hours(24),
+            export_sperrfrist: Duration::hours(24),
+        }
+    }
+}
+
+/// Angelegter, noch nicht geschriebener Export
+#[derive(Debug, Clone)]
+pub struct ExportAuftrag {
+    pub export_id: Uuid,
+    pub user_id: Uuid,
+    storage_path: String,
+    token: String,
+    expires_at: DateTime<Utc>,
+}
+
+/// Fertig geschriebener Export
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FertigerExport {
+    pub export_id: Uuid,
+    /// Einmal-Link zum Herunterladen
+    pub download_url: String,
+    pub expires_at: DateTime<Utc>,
+    pub size_bytes: u64,
+}
+
+/// Inhalt des Export-Archivs
+#[derive(Serialize)]
+struct ExportArchiv<'a> {
+    format: &'static str,
+    erstellt_am: DateTime<Utc>,
+    #[serde(flatten)]
+    daten: &'a KontoDaten,
+}
+
+/// KontoService verwaltet Datenexporte und Kontoloeschungen
+pub struct KontoService<K, S>
+where
+    K: KontoRepository,
+    S: StorageBackend,
+{
+    repo: Arc<K>,
+    storage: Arc<S>,
+    konfig: KontoKonfig,
+}
+
+impl<K, S> KontoService<K, S>
+where
+    K: KontoRepository,
+    S: StorageBackend,
+{
+    /// Neuen KontoService erstellen
+    pub fn neu(repo: Arc<K>, storage: Arc<S>, konfig: KontoKonfig) -> Arc<Self> {
+        Arc::new(Self {
+            repo,
+            storage,
+            konfig,
+        })
+    }
+
+    /// Legt einen Export an
+    ///
+    /// Mit `begrenzt` gilt die Sperrfrist (Selbstbedienung); Administratoren
+    /// duerfen jederzeit exportieren. Abgelaufene Exporte werden vorher
+    /// aufgeraeumt.
+    pub async fn export_anfordern(
+        &self,
+        user_id: Uuid,
+        begrenzt: bool,
+    ) -> ChatResult<ExportAuftrag> {
+        self.abgelaufene_entfernen().await?;
+
+        let jetzt = Utc::now();
+        let export_id = Uuid::new_v4();
+        let token = format!("{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple());
+        let storage_path = format!("exporte/{user_id}/{export_id}.json");
+        let expires_at = jetzt + self.konfig.export_gueltigkeit;
+
+        let record = self
+            .repo
+            .export_anlegen(NeuerKontoExport {
+                user_id,
+                token_hash: &token_hash(&token),
+                storage_path: &storage_path,
+                expires_at,
+                gesperrt_seit: begrenzt.then(|| jetzt - self.konfig.export_sperrfrist),
+            })
+            .await?
+            .ok_or(ChatError::ZuHaeufig {
+                // Obergrenze: der letzte Export liegt hoechstens so weit zurueck
+                retry_after_secs: self.konfig.export_sperrfrist.num_seconds().max(0) as u64,
+            })?;
+
+        tracing::info!(user_id = %user_id, export_id = %record.id, "Konto-Export angelegt");
+
+        Ok(ExportAuftrag {
+            export_id: record.id,
+            user_id,
+            storage_path: record.storage_path,
+            token,
+            expires_at: record.expires_at,
+        })
+    }
+
+    /// Sammelt die Daten und schreibt das Archiv
+    ///
+    /// Schlaegt ein Schritt fehl, wird der Export wieder entfernt.
+    pub async fn export_erstellen(&self, auftrag: &ExportAuftrag) -> ChatResult<FertigerExport> {
+        match self.archiv_schreiben(auftrag).await {
+            Ok(size_bytes) => {
+                tracing::info!(
+                    user_id = %auftrag.user_id,
+                    export_id = %auftrag.export_id,
+                    bytes = size_bytes,
+                    "Konto-Export erstellt"
+                );
+                Ok(FertigerExport {
+                    export_id: auftrag.export_id,
+                    download_url: format!(
+                        "{}/{}",
+                        self.konfig.download_basis_url.trim_end_matches('/'),
+                        auftrag.token
+                    ),
+                    expires_at: auftrag.expires_at,
+                    size_bytes,
+                })
+            }
+            Err(e) => {
+                if let Err(fehler) = self.storage.delete(&auftrag.storage_path).await {
+                    tracing::warn!(pfad = %auftrag.storage_path, %fehler, "Export-Archiv nicht entfernt");
+                }
+                if let Err(fehler) = self.repo.export_entfernen(auftrag.export_id).await {
+                    tracing::warn!(export_id = %auftrag.export_id, %fehler, "Export nicht entfernt");
+                }
+                Err(e)
+            }
+        }
+    }
+
+    async fn archiv_schreiben(&self, auftrag: &ExportAuftrag) -> ChatResult<u64> {
+        let daten = self
+            .repo
+            .daten_sammeln(auftrag.user_id)
+            .await?
+            .ok_or_else(|| ChatError::UngueltigeEingabe("Benutzer existiert nicht mehr".into()))?;
+        let archiv = serde_json::to_vec_pretty(&ExportArchiv {
+            format: EXPORT_FORMAT,
+            erstellt_am: Utc::now(),
+            daten: &daten,
+        })
+        .map_err(anyhow::Error::from)?;
+
+        self.storage.store(&auftrag.storage_path, &archiv).await?;
+        self.repo
+            .export_abschliessen(auftrag.export_id, archiv.len() as i64)
+            .await?;
+        Ok(archiv.len() as u64)
+    }
+
+    /// Loest einen Download-Token ein und gibt das Archiv zurueck
+    ///
+    /// Danach ist der Token verbraucht und das Archiv entfernt.
+    pub async fn export_abholen(&self, token: &str) -> ChatResult<Vec<u8>> {
+        let record = self
+            .repo
+            .export_einloesen(&token_hash(token), Utc::now())
+            .await?
+            .ok_or_else(|| {
+                ChatError::TokenUngueltig(
+                    "Export unbekannt, abgelaufen oder bereits abgeholt".into(),
+                )
+            })?;
+
+        let archiv = self.storage.retrieve(&record.storage_path).await?;
+        if let Err(fehler) = self.storage.delete(&record.storage_path).await {
+            tracing::warn!(pfad = %record.storage_path, %fehler, "Export-Archiv nicht entfernt");
+        }
+        tracing::info!(user_id = %record.user_id, export_id = %record.id, "Konto-Export abgeholt");
+        Ok(archiv)
+    }
+
+    /// Entfernt abgelaufene Exporte samt Archiv
+    pub async fn abgelaufene_entfernen(&self) -> ChatResult<usize> {
+        let abgelaufen = self.repo.exporte_ablaufen(Utc::now()).await?;
+        for export in &abgelaufen {
+            if let Err(fehler) = self.storage.delete(&export.storage_path).await {
+                tracing::warn!(pfad = %export.storage_path, %fehler, "Export-Archiv nicht entfernt");
+            }
+        }
+        Ok(abgelaufen.len())
+    }
+
+    /// Loescht ein Konto samt Dateien und Export-Archiven
+    ///
+    /// Sessions und API-Tokens beendet der Aufrufer ueber den AuthService.
+    /// Nicht entfernbare Speicherdateien werden nur geloggt: die Datensaetze
+    /// sind zu diesem Zeitpunkt bereits geloescht.
+    pub async fn konto_loeschen(
+        &self,
+        user_id: Uuid,
+        admin_id: Option<Uuid>,
+    ) -> ChatResult<KontoLoeschung> {
+        let loeschung = self
+            .repo
+            .konto_loeschen(KontoLoeschAuftrag {
+                user_id,
+                richtlinie: self.konfig.nachrichten_richtlinie,
+                admin_id,
+                kontingent_gruppe: DEFAULT_GROUP,
+            })
+            .await?;
+
+        let pfade = loeschung
+            .dateien
+            .iter()
+            .map(|d| &d.storage_path)
+            .chain(&loeschung.exporte);
+        for pfad in pfade {
+            if let Err(fehler) = self.storage.delete(pfad).await {
+                tracing::warn!(pfad = %pfad, %fehler, "Speicherdatei nicht entfernt");
+            }
+        }
+
+        tracing::info!(
+            user_id = %user_id,
+            nachrichten = loeschung.nachrichten,
+            dateien = loeschung.dateien.len(),
+            berechtigungen = loeschung.berechtigungen,
+            "Konto geloescht"
+        );
+        Ok(loeschung)
+    }
+
+    /// Legt einen Auftrag zur Inhaltsbereinigung an
+    pub async fn bereinigung_anlegen(
+        &self,
+        data: NeueInhaltsBereinigung,
+    ) -> ChatResult<InhaltsBereinigungRecord> {
+        Ok(self.repo.bereinigung_anlegen(data).await?)
+    }
+
+    /// Laedt einen Bereinigungsauftrag
+    pub async fn bereinigung_laden(&self, id: Uuid) -> ChatResult<InhaltsBereinigungRecord> {
+        self.repo
+            .bereinigung_laden(id)
+            .await?
+            .ok_or_else(|| DbError::nicht_gefunden(format!("Inhaltsbereinigung {id}")).into())
+    }
+
+    /// Loescht den naechsten Stapel eines Auftrags samt Speicherdateien
+    ///
+    /// Wie beim Loeschen einzelner Dateien: erst Datensatz und Kontingent,
+    /// danach die Speicherdatei; Fehler beim Entfernen werden nur geloggt.
+    pub async fn bereinigung_stapel(&self, id: Uuid, limit: i64) -> ChatResult<InhaltsStapel> {
+        let stapel = self
+            .repo
+            .bereinigung_stapel(id, limit, DEFAULT_GROUP)
+            .await?;
+        for datei in &stapel.dateien {
+            if let Err(fehler) = self.storage.delete(&datei.storage_path).await {
+                tracing::warn!(pfad = %datei.storage_path, %fehler, "Speicherdatei nicht entfernt");
+            }
+        }
+        Ok(stapel)
+    }
+
+    /// Markiert einen Bereinigungsauftrag als abgeschlossen
+    pub async fn bereinigung_abschliessen(&self, id: Uuid) -> ChatResult<InhaltsBereinigungRecord> {
+        Ok(self.repo.bereinigung_abschliessen(id).await?)
+    }
+}
+
+fn token_hash(token: &str) -> String {
+    format!("{:x}", Sha256::digest(token.as_bytes()))
+}
+
+/// Rueckgabe der [`KontoDienst`]-Methoden
+pub type KontoFuture<'a, T> = Pin<Box<dyn Future<Output = ChatResult<T>> + Send + 'a>>;
+
+/// Objektsichere Sicht auf den [`KontoService`]
+///
+/// Fuer Signaling und Commander, die den Speicher-Typ nicht kennen. Nur fuer
+/// konkrete Typen implementiert: erst dort ist sichtbar, dass die Futures
+/// der Repositories `Send` sind.
+pub trait KontoDienst: Send + Sync {
+    fn export_anfordern(&self, user_id: Uuid, begrenzt: bool) -> KontoFuture<'_, ExportAuftrag>;
+
+    fn export_erstellen<'a>(
+        &'a self,
+        auftrag: &'a ExportAuftrag,
+    ) -> KontoFuture<'a, FertigerExport>;
+
+    fn export_abholen<'a>(&'a self, token: &'a str) -> KontoFuture<'a, Vec<u8>>;
+
+    fn konto_loeschen(
+        &self,
+        user_id: Uuid,
+        admin_id: Option<Uuid>,
+    ) -> KontoFuture<'_, KontoLoeschung>;
+
+    fn bereinigung_anlegen(
+        &self,
+        data: NeueInhaltsBereinigung,
+    ) -> KontoFuture<'_, InhaltsBereinigungRecord>;
+
+    fn bereinigung_laden(&self, id: Uuid) -> KontoFuture<'_, InhaltsBereinigungRecord>;
+
+    fn bereinigung_stapel(&self, id: Uuid, limit: i64) -> KontoFuture<'_, InhaltsStapel>;
+
+    fn bereinigung_abschliessen(&self, id: Uuid) -> KontoFuture<'_, InhaltsBereinigungRecord>;
+}
+
+impl KontoDienst for KontoService<SqliteDb, DiskStorage> {
+    fn export_anfordern(&self, user_id: Uuid, begrenzt: bool) -> KontoFuture<'_, ExportAuftrag> {
+        Box::pin(KontoService::export_anfordern(self, user_id, begrenzt))
+    }
+
+    fn export_erstellen<'a>(
+        &'a self,
+        auftrag: &'a ExportAuftrag,
+    ) -> KontoFuture<'a, FertigerExport> {
+        Box::pin(KontoService::export_erstellen(self, auftrag))
+    }
+
+    fn export_abholen<'a>(&'a self, token: &'a str) -> KontoFuture<'a, Vec<u8>> {
+        Box::pin(KontoService::export_abholen(self, token))
+    }
+
+    fn konto_loeschen(
+        &self,
+        user_id: Uuid,
+        admin_id: Option<Uuid>,
+    ) -> KontoFuture<'_, KontoLoeschung> {
+        Box::pin(KontoService::konto_loeschen(self, user_id, admin_id))
+    }
+
+    fn bereinigung_anlegen(
+        &self,
+        data: NeueInhaltsBereinigung,
+    ) -> KontoFuture<'_, InhaltsBereinigungRecord> {
+        Box::pin(KontoService::bereinigung_anlegen(self, data))
+    }
+
+    fn bereinigung_laden(&self, id: Uuid) -> KontoFuture<'_, InhaltsBereinigungRecord> {
+        Box::pin(KontoService::bereinigung_laden(self, id))
+    }
+
+    fn bereinigung_stapel(&self, id: Uuid, limit: i64) -> KontoFuture<'_, InhaltsStapel> {
+        Box::pin(KontoService::bereinigung_stapel(self, id, limit))
+    }
+
+    fn bereinigung_abschliessen(&self, id: Uuid) -> KontoFuture<'_, InhaltsBereinigungRecord> {
+        Box::pin(KontoService::bereinigung_abschliessen(self, id))
+    }
+}
+
+impl KontoDienst for KontoService<Datenbank, DiskStorage> {
+    fn export_anfordern(&self, user_id: Uuid, begrenzt: bool) -> KontoFuture<'_, ExportAuftrag> {
+        Box::pin(KontoService::export_anfordern(self, user_id, begrenzt))
+    }
+
+    fn export_erstellen<'a>(
+        &'a self,
+        auftrag: &'a ExportAuftrag,
+    ) -> KontoFuture<'a, FertigerExport> {
+        Box::pin(KontoService::export_erstellen(self, auftrag))
+    }
+
+    fn export_abholen<'a>(&'a self, token: &'a str) -> KontoFuture<'a, Vec<u8>> {
+        Box::pin(KontoService::export_abholen(self, token))
+    }
+
+    fn konto_loeschen(
+        &self,
+        user_id: Uuid,
+        admin_id: Option<Uuid>,
+    ) -> KontoFuture<'_, KontoLoeschung> {
+        Box::pin(KontoService::konto_loeschen(self, user_id, admin_id))
+    }
+
+    fn bereinigung_anlegen(
+        &self,
+        data: NeueInhaltsBereinigung,
+    ) -> KontoFuture<'_, InhaltsBereinigungRecord> {
+        Box::pin(KontoService::bereinigung_anlegen(self, data))
+    }
+
+    fn bereinigung_laden(&self, id: Uuid) -> KontoFuture<'_, InhaltsBereinigungRecord> {
+        Box::pin(KontoService::bereinigung_laden(self, id))
+    }
+
+    fn bereinigung_stapel(&self, id: Uuid, limit: i64) -> KontoFuture<'_, InhaltsStapel> {
+        Box::pin(KontoService::bereinigung_stapel(self, id, limit))
+    }
+
+    fn bereinigung_abschliessen(&self, id: Uuid) -> KontoFuture<'_, InhaltsBereinigungRecord> {
+        Box::pin(KontoService::bereinigung_abschliessen(self, id))
+    }
+}
@@ -3,7 +3,11 @@
 //! Dieses Crate implementiert:
 //! - ChatService: Nachrichten senden, editieren, loeschen, History, Suche
 //! - FileService: Datei-Upload/Download mit Quota-Pruefung und SHA-256
+//! - DownloadService: signierte, zeitlich begrenzte Download-Links
+//! - UploadService: signierte Upload-Links, Inhalt folgt per HTTP
+//! - KontoService: Datenexport und Loeschung ganzer Benutzerkonten
 //! - StorageBackend-Trait + DiskStorage-Implementierung
+//! - ZugriffsLogger: gepuffertes Zugriffsprotokoll fuer Datei-Downloads
 //!
 //! # Beispiel
 //!
@@ -26,18 +30,31 @@
 //! }
 //! ```
 
+pub mod download_service;
 pub mod error;
 pub mod file_service;
+pub mod konto_service;
 pub mod service;
 pub mod storage;
 pub mod types;
+pub mod upload_service;
+pub mod zugriffs_log;
 
 #[cfg(test)]
 mod tests;
 
 // Bequeme Re-Exporte
+pub use download_service::{DateiDienst, DownloadAngebot, DownloadKonfig, DownloadService};
 pub use error::{ChatError, ChatResult};
 pub use file_service::FileService;
+pub use konto_service::{ExportAuftrag, FertigerExport, KontoDienst, KontoKonfig, KontoService};
 pub use service::ChatService;
 pub use storage::{DiskStorage, StorageBackend};
-pub use types::{ChatNachricht, DateeiInfo, DateiUpload, HistoryAnfrage, NachrichtenTyp};
+pub use types::{
+    AenderungsRecht, ChatNachricht, DateeiInfo, DateiUpload, HistoryAnfrage, NachrichtenTyp,
+    SuchAnfrage, UploadAnmeldung, Zwischendatei,
+};
+pub use upload_service::{
+    UploadAngebot, UploadDienst, UploadFreigabe, UploadKonfig, UploadService,
+};
+pub use zugriffs_log::{ZugriffsLogKonfig, ZugriffsLogModus, ZugriffsLogger};
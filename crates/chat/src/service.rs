@@ -2,18 +2,25 @@
 
 use std::sync::Arc;
 
+use chrono::Utc;
 use uuid::Uuid;
 
 use speakeasy_db::{
-    models::{NachrichtenFilter, NachrichtenTyp as DbNachrichtenTyp, NeueNachricht},
+    models::{
+        NachrichtenFilter, NachrichtenSuche, NachrichtenTyp as DbNachrichtenTyp, NeueNachricht,
+    },
     ChatMessageRepository,
 };
+use speakeasy_protocol::control::MAX_NACHRICHT_ZEICHEN;
 
 use crate::{
     error::{ChatError, ChatResult},
-    types::{ChatNachricht, HistoryAnfrage, NachrichtenTyp},
+    types::{AenderungsRecht, ChatNachricht, HistoryAnfrage, NachrichtenTyp, SuchAnfrage},
 };
 
+/// Maximale Laenge des Suchtexts in Zeichen
+const SUCHE_MAX_ZEICHEN: usize = 200;
+
 /// ChatService verwaltet Text-Nachrichten in Kanaelen
 pub struct ChatService<R: ChatMessageRepository> {
     repo: Arc<R>,
@@ -39,12 +46,7 @@ impl<R: ChatMessageRepository> ChatService<R> {
             ));
         }
 
-        if content.len() > 4096 {
-            return Err(ChatError::UngueltigeEingabe(format!(
-                "Nachricht zu lang: {} Zeichen (Maximum: 4096)",
-                content.len()
-            )));
-        }
+        inhalt_pruefen(content)?;
 
         let record = self
             .repo
@@ -60,12 +62,13 @@ impl<R: ChatMessageRepository> ChatService<R> {
         Ok(record_to_nachricht(record, None))
     }
 
-    /// Nachricht editieren (nur eigene Nachrichten)
+    /// Nachricht editieren (nur eigene Nachrichten, innerhalb der Frist)
     pub async fn nachricht_editieren(
         &self,
         message_id: Uuid,
         sender_id: Uuid,
         new_content: &str,
+        recht: AenderungsRecht,
     ) -> ChatResult<ChatNachricht> {
         if new_content.trim().is_empty() {
             return Err(ChatError::UngueltigeEingabe(
@@ -73,12 +76,7 @@ impl<R: ChatMessageRepository> ChatService<R> {
             ));
         }
 
-        if new_content.len() > 4096 {
-            return Err(ChatError::UngueltigeEingabe(format!(
-                "Nachricht zu lang: {} Zeichen (Maximum: 4096)",
-                new_content.len()
-            )));
-        }
+        inhalt_pruefen(new_content)?;
 
         // Nachricht laden und Berechtigung pruefen
         let existing = self
@@ -96,31 +94,41 @@ impl<R: ChatMessageRepository> ChatService<R> {
         if existing.deleted_at.is_some() {
             return Err(ChatError::NachrichtNichtGefunden(message_id.to_string()));
         }
+        recht.pruefen(existing.created_at, Utc::now())?;
 
         let record = self.repo.update_content(message_id, new_content).await?;
         Ok(record_to_nachricht(record, None))
     }
 
     /// Nachricht weich loeschen (Soft-Delete)
-    pub async fn nachricht_loeschen(&self, message_id: Uuid, requester_id: Uuid) -> ChatResult<()> {
+    ///
+    /// Verfasser innerhalb der Frist, Moderatoren jederzeit. Gibt die
+    /// Nachricht im Zustand vor dem Loeschen zurueck.
+    pub async fn nachricht_loeschen(
+        &self,
+        message_id: Uuid,
+        requester_id: Uuid,
+        recht: AenderungsRecht,
+    ) -> ChatResult<ChatNachricht> {
         let existing = self
             .repo
             .get_by_id(message_id)
             .await?
             .ok_or_else(|| ChatError::NachrichtNichtGefunden(message_id.to_string()))?;
 
-        if existing.sender_id != requester_id {
+        if existing.sender_id != requester_id && !recht.moderator {
             return Err(ChatError::KeineBerechtigung(
                 "Nur der Verfasser kann die Nachricht loeschen".into(),
             ));
         }
+        recht.pruefen(existing.created_at, Utc::now())?;
 
         let geloescht = self.repo.soft_delete(message_id).await?;
         if !geloescht {
             return Err(ChatError::NachrichtNichtGefunden(message_id.to_string()));
         }
 
-        Ok(())
+        Ok(record_to_nachricht(existing, None))
     }
 
     /// Nachrichten-History eines Kanals laden (Cursor-Pagination)
@@ -140,19 +148,39 @@ impl<R: ChatMessageRepository> ChatService<R> {
             .collect())
     }
 
-    /// Nachrichten eines Kanals durchsuchen
-    pub async fn nachrichten_suchen(
-        &self,
-        channel_id: Uuid,
-        query: &str,
-    ) -> ChatResult<Vec<ChatNachricht>> {
-        if query.trim().is_empty() {
+    /// Nachrichten eines Kanals durchsuchen (neueste zuerst, ohne geloeschte)
+    ///
+    /// Der Suchtext wird woertlich gesucht, LIKE-Platzhalter darin haben
+    /// keine Sonderbedeutung.
+    pub async fn suchen(&self, anfrage: SuchAnfrage) -> ChatResult<Vec<ChatNachricht>> {
+        let query = anfrage.query.trim();
+        if query.is_empty() {
             return Err(ChatError::UngueltigeEingabe(
                 "Suchbegriff darf nicht leer sein".into(),
             ));
         }
+        if query.chars().count() > SUCHE_MAX_ZEICHEN {
+            return Err(ChatError::UngueltigeEingabe(format!(
+                "Suchbegriff darf hoechstens {SUCHE_MAX_ZEICHEN} Zeichen lang sein"
+            )));
+        }
+        if anfrage.offset < 0 {
+            return Err(ChatError::UngueltigeEingabe(
+                "Offset darf nicht negativ sein".into(),
+            ));
+        }
 
-        let records = self.repo.search(channel_id, query, 50).await?;
+        let records = self
+            .repo
+            .search(NachrichtenSuche {
+                channel_id: anfrage.channel_id,
+                query,
+                before: anfrage.before,
+                before_id: anfrage.before_id,
+                limit: anfrage.seitengroesse(),
+                offset: anfrage.offset,
+            })
+            .await?;
         Ok(records
             .into_iter()
             .map(|r| record_to_nachricht(r, None))
@@ -160,6 +188,17 @@ impl<R: ChatMessageRepository> ChatService<R> {
     }
 }
 
+/// Prueft die Laenge eines Nachrichteninhalts (in Zeichen, nicht Bytes)
+fn inhalt_pruefen(inhalt: &str) -> ChatResult<()> {
+    let zeichen = inhalt.chars().count();
+    if zeichen > MAX_NACHRICHT_ZEICHEN {
+        return Err(ChatError::UngueltigeEingabe(format!(
+            "Nachricht zu lang: {zeichen} Zeichen (Maximum: {MAX_NACHRICHT_ZEICHEN})"
+        )));
+    }
+    Ok(())
+}
+
 /// Konvertiert einen DB-Record in den Domain-Typ
 fn record_to_nachricht(
     record: speakeasy_db::models::ChatNachrichtRecord,
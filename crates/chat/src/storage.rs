@@ -2,7 +2,7 @@
 //!
 //! Das `StorageBackend`-Trait abstrahiert den konkreten Speicher (Disk, S3, etc.).
 
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use crate::error::ChatResult;
 
@@ -12,6 +12,9 @@ pub trait StorageBackend: Send + Sync {
     /// Datei unter dem angegebenen Pfad speichern
     async fn store(&self, path: &str, data: &[u8]) -> ChatResult<()>;
 
+    /// Zwischendatei unter dem angegebenen Pfad ablegen (verschiebt sie)
+    async fn store_file(&self, path: &str, quelle: &Path) -> ChatResult<()>;
+
     /// Datei laden
     async fn retrieve(&self, path: &str) -> ChatResult<Vec<u8>>;
 
@@ -55,6 +58,21 @@ impl StorageBackend for DiskStorage {
         Ok(())
     }
 
+    async fn store_file(&self, path: &str, quelle: &Path) -> ChatResult<()> {
+        let full = self.full_path(path);
+        if let Some(parent) = full.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+
+        // Umbenennen scheitert ueber Dateisystemgrenzen, dann kopieren
+        if tokio::fs::rename(quelle, &full).await.is_err() {
+            tokio::fs::copy(quelle, &full).await?;
+            tokio::fs::remove_file(quelle).await?;
+        }
+        tracing::debug!(path = %full.display(), "Datei aus Zwischendatei gespeichert");
+        Ok(())
+    }
+
     async fn retrieve(&self, path: &str) -> ChatResult<Vec<u8>> {
         let full = self.full_path(path);
         let data = tokio::fs::read(&full).await?;
@@ -4,13 +4,22 @@ use std::sync::Arc;
 
 #[allow(unused_imports)]
 use chrono;
-use speakeasy_db::models::{KanalTyp, NeuerBenutzer, NeuerKanal};
+use speakeasy_db::models::{
+    ChatNachrichtRecord, KanalTyp, NachrichtenFilter, NachrichtenSuche, NeueNachricht,
+    NeuerBenutzer, NeuerKanal,
+};
 use speakeasy_db::ChannelRepository;
+use speakeasy_db::ChatMessageRepository;
+use speakeasy_db::DbResult;
 use speakeasy_db::SqliteDb;
 use speakeasy_db::UserRepository;
 use uuid::Uuid;
 
-use crate::{error::ChatError, service::ChatService, types::HistoryAnfrage};
+use crate::{
+    error::ChatError,
+    service::ChatService,
+    types::{AenderungsRecht, HistoryAnfrage, SuchAnfrage},
+};
 
 async fn test_db() -> Arc<SqliteDb> {
     Arc::new(
@@ -109,6 +118,26 @@ async fn test_zu_lange_nachricht_abgelehnt() {
     assert!(matches!(result, Err(ChatError::UngueltigeEingabe(_))));
 }
 
+#[tokio::test]
+async fn test_laenge_zaehlt_zeichen_nicht_bytes() {
+    let db = test_db().await;
+    let (channel_id, sender_id) = setup_kanal_und_user(&db).await;
+    let service = ChatService::neu(db);
+
+    // 4096 Umlaute sind 8192 Bytes, aber genau an der Grenze
+    let grenze = "\u{e4}".repeat(4096);
+    service
+        .nachricht_senden(channel_id, sender_id, &grenze, None)
+        .await
+        .unwrap();
+
+    let zu_lang = "\u{e4}".repeat(4097);
+    let result = service
+        .nachricht_senden(channel_id, sender_id, &zu_lang, None)
+        .await;
+    assert!(matches!(result, Err(ChatError::UngueltigeEingabe(_))));
+}
+
 #[tokio::test]
 async fn test_nachricht_editieren() {
     let db = test_db().await;
@@ -121,7 +150,12 @@ async fn test_nachricht_editieren() {
         .expect("Nachricht senden fehlgeschlagen");
 
     let editiert = service
-        .nachricht_editieren(nachricht.id, sender_id, "Editiert")
+        .nachricht_editieren(
+            nachricht.id,
+            sender_id,
+            "Editiert",
+            AenderungsRecht::default(),
+        )
         .await
         .expect("Nachricht editieren fehlgeschlagen");
 
@@ -151,7 +185,12 @@ async fn test_fremde_nachricht_nicht_editierbar() {
     .expect("User anlegen fehlgeschlagen");
 
     let result = service
-        .nachricht_editieren(nachricht.id, anderer_user.id, "Nicht erlaubt")
+        .nachricht_editieren(
+            nachricht.id,
+            anderer_user.id,
+            "Nicht erlaubt",
+            AenderungsRecht::default(),
+        )
         .await;
 
     assert!(matches!(result, Err(ChatError::KeineBerechtigung(_))));
@@ -168,10 +207,12 @@ async fn test_nachricht_loeschen() {
         .await
         .expect("Nachricht senden fehlgeschlagen");
 
-    service
-        .nachricht_loeschen(nachricht.id, sender_id)
+    let geloescht = service
+        .nachricht_loeschen(nachricht.id, sender_id, AenderungsRecht::default())
         .await
         .expect("Nachricht loeschen fehlgeschlagen");
+    assert_eq!(geloescht.id, nachricht.id);
+    assert_eq!(geloescht.channel_id, channel_id);
 }
 
 #[tokio::test]
@@ -196,7 +237,7 @@ async fn test_fremde_nachricht_nicht_loeschbar() {
     .expect("User anlegen fehlgeschlagen");
 
     let result = service
-        .nachricht_loeschen(nachricht.id, anderer_user.id)
+        .nachricht_loeschen(nachricht.id, anderer_user.id, AenderungsRecht::default())
         .await;
 
     assert!(matches!(result, Err(ChatError::KeineBerechtigung(_))));
@@ -312,11 +353,107 @@ async fn test_nachrichten_suchen() {
         .unwrap();
 
     let ergebnisse = service
-        .nachrichten_suchen(channel_id, "Guten")
+        .suchen(suche(channel_id, "Guten"))
         .await
         .expect("Suche fehlgeschlagen");
 
     assert_eq!(ergebnisse.len(), 2);
+    // Neueste zuerst
+    assert_eq!(ergebnisse[0].content, "Guten Abend!");
+    assert_eq!(ergebnisse[1].content, "Guten Morgen!");
+}
+
+fn suche(channel_id: Uuid, query: &str) -> SuchAnfrage {
+    SuchAnfrage {
+        channel_id,
+        query: query.into(),
+        ..Default::default()
+    }
+}
+
+#[tokio::test]
+async fn test_suche_sonderzeichen_woertlich() {
+    let db = test_db().await;
+    let (channel_id, sender_id) = setup_kanal_und_user(&db).await;
+    let service = ChatService::neu(db);
+
+    for inhalt in [
+        "100% sicher",
+        "1000 sicher",
+        "datei_name.txt",
+        "dateiXname.txt",
+        "Das ist 'zitiert'",
+        "Er sagte \"hallo\"",
+        "C:\\pfad\\datei",
+    ] {
+        service
+            .nachricht_senden(channel_id, sender_id, inhalt, None)
+            .await
+            .unwrap();
+    }
+
+    let inhalte = |treffer: Vec<crate::types::ChatNachricht>| {
+        treffer.into_iter().map(|n| n.content).collect::<Vec<_>>()
+    };
+
+    let treffer = service.suchen(suche(channel_id, "0%")).await.unwrap();
+    assert_eq!(inhalte(treffer), ["100% sicher"]);
+
+    let treffer = service.suchen(suche(channel_id, "i_n")).await.unwrap();
+    assert_eq!(inhalte(treffer), ["datei_name.txt"]);
+
+    let treffer = service
+        .suchen(suche(channel_id, "'zitiert'"))
+        .await
+        .unwrap();
+    assert_eq!(inhalte(treffer), ["Das ist 'zitiert'"]);
+
+    let treffer = service
+        .suchen(suche(channel_id, "\"hallo\""))
+        .await
+        .unwrap();
+    assert_eq!(inhalte(treffer), ["Er sagte \"hallo\""]);
+
+    let treffer = service.suchen(suche(channel_id, "\\pfad\\")).await.unwrap();
+    assert_eq!(inhalte(treffer), ["C:\\pfad\\datei"]);
+
+    let treffer = service.suchen(suche(channel_id, "%")).await.unwrap();
+    assert_eq!(inhalte(treffer), ["100% sicher"]);
+}
+
+#[tokio::test]
+async fn test_suche_ohne_geloeschte_und_mit_offset() {
+    let db = test_db().await;
+    let (channel_id, sender_id) = setup_kanal_und_user(&db).await;
+    let service = ChatService::neu(db);
+
+    let mut ids = Vec::new();
+    for inhalt in ["Treffer eins", "Treffer zwei", "Treffer drei"] {
+        let n = service
+            .nachricht_senden(channel_id, sender_id, inhalt, None)
+            .await
+            .unwrap();
+        ids.push(n.id);
+    }
+    service
+        .nachricht_loeschen(ids[1], sender_id, AenderungsRecht::default())
+        .await
+        .unwrap();
+
+    let alle = service.suchen(suche(channel_id, "Treffer")).await.unwrap();
+    let inhalte: Vec<_> = alle.iter().map(|n| n.content.as_str()).collect();
+    assert_eq!(inhalte, ["Treffer drei", "Treffer eins"]);
+
+    let zweite_seite = service
+        .suchen(SuchAnfrage {
+            limit: Some(1),
+            offset: 1,
+            ..suche(channel_id, "Treffer")
+        })
+        .await
+        .unwrap();
+    assert_eq!(zweite_seite.len(), 1);
+    assert_eq!(zweite_seite[0].content, "Treffer eins");
 }
 
 #[tokio::test]
@@ -325,7 +462,11 @@ async fn test_suche_leerer_query_abgelehnt() {
     let (channel_id, _) = setup_kanal_und_user(&db).await;
     let service = ChatService::neu(db);
 
-    let result = service.nachrichten_suchen(channel_id, "  ").await;
+    let result = service.suchen(suche(channel_id, "  ")).await;
+    assert!(matches!(result, Err(ChatError::UngueltigeEingabe(_))));
+
+    let zu_lang = "x".repeat(201);
+    let result = service.suchen(suche(channel_id, &zu_lang)).await;
     assert!(matches!(result, Err(ChatError::UngueltigeEingabe(_))));
 }
 
@@ -344,7 +485,10 @@ async fn test_geloeschte_nachrichten_nicht_in_history() {
         .await
         .unwrap();
 
-    service.nachricht_loeschen(n2.id, sender_id).await.unwrap();
+    service
+        .nachricht_loeschen(n2.id, sender_id, AenderungsRecht::default())
+        .await
+        .unwrap();
 
     let history = service
         .history_laden(HistoryAnfrage {
@@ -358,3 +502,221 @@ async fn test_geloeschte_nachrichten_nicht_in_history() {
     assert_eq!(history.len(), 1);
     assert_eq!(history[0].id, n1.id);
 }
+
+/// Liefert Nachrichten mit um `versatz` vorverlegtem Sendezeitpunkt, damit
+/// Fristen ohne Warten ablaufen
+struct Vordatiert {
+    db: Arc<SqliteDb>,
+    versatz: chrono::Duration,
+}
+
+impl ChatMessageRepository for Vordatiert {
+    async fn create(&self, data: NeueNachricht<'_>) -> DbResult<ChatNachrichtRecord> {
+        ChatMessageRepository::create(self.db.as_ref(), data).await
+    }
+
+    async fn get_by_id(&self, id: Uuid) -> DbResult<Option<ChatNachrichtRecord>> {
+        let record = ChatMessageRepository::get_by_id(self.db.as_ref(), id).await?;
+        Ok(record.map(|mut r| {
+            r.created_at -= self.versatz;
+            r
+        }))
+    }
+
+    async fn get_history(&self, filter: NachrichtenFilter) -> DbResult<Vec<ChatNachrichtRecord>> {
+        self.db.get_history(filter).await
+    }
+
+    async fn update_content(&self, id: Uuid, new_content: &str) -> DbResult<ChatNachrichtRecord> {
+        self.db.update_content(id, new_content).await
+    }
+
+    async fn soft_delete(&self, id: Uuid) -> DbResult<bool> {
+        self.db.soft_delete(id).await
+    }
+
+    async fn search(&self, suche: NachrichtenSuche<'_>) -> DbResult<Vec<ChatNachrichtRecord>> {
+        self.db.search(suche).await
+    }
+}
+
+#[test]
+fn test_frist_genau_an_der_grenze() {
+    let gesendet = chrono::Utc::now();
+    let recht = AenderungsRecht {
+        frist_sek: Some(900),
+        moderator: false,
+    };
+
+    assert!(recht
+        .pruefen(gesendet, gesendet + chrono::Duration::seconds(900))
+        .is_ok());
+    let result = recht.pruefen(
+        gesendet,
+        gesendet + chrono::Duration::seconds(900) + chrono::Duration::milliseconds(1),
+    );
+    assert!(matches!(
+        result,
+        Err(ChatError::FristAbgelaufen { frist_sek: 900 })
+    ));
+
+    // Moderatoren und Kanaele ohne Frist sind nicht begrenzt
+    let spaeter = gesendet + chrono::Duration::days(30);
+    let moderator = AenderungsRecht {
+        moderator: true,
+        ..recht
+    };
+    assert!(moderator.pruefen(gesendet, spaeter).is_ok());
+    assert!(AenderungsRecht::default()
+        .pruefen(gesendet, spaeter)
+        .is_ok());
+}
+
+#[tokio::test]
+async fn test_editieren_und_loeschen_nach_ablauf_der_frist() {
+    let db = test_db().await;
+    let (channel_id, sender_id) = setup_kanal_und_user(&db).await;
+    let service = ChatService::neu(Arc::new(Vordatiert {
+        db: db.clone(),
+        versatz: chrono::Duration::minutes(16),
+    }));
+    let recht = AenderungsRecht {
+        frist_sek: Some(15 * 60),
+        moderator: false,
+    };
+
+    let nachricht = service
+        .nachricht_senden(channel_id, sender_id, "Original", None)
+        .await
+        .unwrap();
+
+    let result = service
+        .nachricht_editieren(nachricht.id, sender_id, "Umgeschrieben", recht)
+        .await;
+    assert!(matches!(result, Err(ChatError::FristAbgelaufen { .. })));
+    let result = service
+        .nachricht_loeschen(nachricht.id, sender_id, recht)
+        .await;
+    assert!(matches!(result, Err(ChatError::FristAbgelaufen { .. })));
+
+    // Mit laengerer Frist ist dieselbe Nachricht noch aenderbar
+    let editiert = service
+        .nachricht_editieren(
+            nachricht.id,
+            sender_id,
+            "Korrigiert",
+            AenderungsRecht {
+                frist_sek: Some(60 * 60),
+                moderator: false,
+            },
+        )
+        .await
+        .unwrap();
+    assert_eq!(editiert.content, "Korrigiert");
+}
+
+#[tokio::test]
+async fn test_moderator_umgeht_frist_und_loescht_fremde_nachrichten() {
+    let db = test_db().await;
+    let (channel_id, sender_id) = setup_kanal_und_user(&db).await;
+    let service = ChatService::neu(Arc::new(Vordatiert {
+        db: db.clone(),
+        versatz: chrono::Duration::hours(2),
+    }));
+    let moderator_id = UserRepository::create(
+        db.as_ref(),
+        NeuerBenutzer {
+            username: "moderator",
+            password_hash: "hash",
+        },
+    )
+    .await
+    .unwrap()
+    .id;
+    let frist = Some(15 * 60);
+
+    let eigene = service
+        .nachricht_senden(channel_id, moderator_id, "Eigene", None)
+        .await
+        .unwrap();
+    let editiert = service
+        .nachricht_editieren(
+            eigene.id,
+            moderator_id,
+            "Eigene, korrigiert",
+            AenderungsRecht {
+                frist_sek: frist,
+                moderator: true,
+            },
+        )
+        .await
+        .unwrap();
+    assert_eq!(editiert.content, "Eigene, korrigiert");
+
+    let fremde = service
+        .nachricht_senden(channel_id, sender_id, "Beleidigung", None)
+        .await
+        .unwrap();
+    // Ohne Moderationsrecht bleibt die fremde Nachricht geschuetzt
+    let result = service
+        .nachricht_loeschen(
+            fremde.id,
+            moderator_id,
+            AenderungsRecht {
+                frist_sek: frist,
+                moderator: false,
+            },
+        )
+        .await;
+    assert!(matches!(result, Err(ChatError::KeineBerechtigung(_))));
+    // Auch Moderatoren editieren keine fremden Nachrichten
+    let result = service
+        .nachricht_editieren(
+            fremde.id,
+            moderator_id,
+            "Zensiert",
+            AenderungsRecht {
+                frist_sek: frist,
+                moderator: true,
+            },
+        )
+        .await;
+    assert!(matches!(result, Err(ChatError::KeineBerechtigung(_))));
+
+    let geloescht = service
+        .nachricht_loeschen(
+            fremde.id,
+            moderator_id,
+            AenderungsRecht {
+                frist_sek: frist,
+                moderator: true,
+            },
+        )
+        .await
+        .unwrap();
+    assert_eq!(geloescht.id, fremde.id);
+}
+
+#[tokio::test]
+async fn test_bearbeitungen_werden_gezaehlt() {
+    let db = test_db().await;
+    let (channel_id, sender_id) = setup_kanal_und_user(&db).await;
+    let service = ChatService::neu(db.clone());
+
+    let nachricht = service
+        .nachricht_senden(channel_id, sender_id, "v1", None)
+        .await
+        .unwrap();
+    for inhalt in ["v2", "v3", "v4"] {
+        service
+            .nachricht_editieren(nachricht.id, sender_id, inhalt, AenderungsRecht::default())
+            .await
+            .unwrap();
+    }
+
+    let record = ChatMessageRepository::get_by_id(db.as_ref(), nachricht.id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(record.edit_count, 3);
+}
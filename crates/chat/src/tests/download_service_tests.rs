@@ -0,0 +1,164 @@
+//! Unit-Tests fuer den DownloadService
+
+use std::sync::Arc;
+
+use chrono::Duration;
+use sha2::{Digest, Sha256};
+use speakeasy_db::models::{KanalTyp, NeuerBenutzer, NeuerKanal};
+use speakeasy_db::{ChannelRepository, SqliteDb, UserRepository};
+use uuid::Uuid;
+
+use crate::{
+    download_service::{DownloadKonfig, DownloadService},
+    error::ChatError,
+    file_service::FileService,
+    storage::DiskStorage,
+    types::DateiUpload,
+};
+
+type Dateien = FileService<SqliteDb, SqliteDb, DiskStorage>;
+
+/// DB mit einer hochgeladenen Datei; gibt FileService, Datei- und User-ID zurueck
+async fn setup(dir: &std::path::Path) -> (Arc<Dateien>, Uuid, Uuid) {
+    let db = Arc::new(
+        SqliteDb::in_memory()
+            .await
+            .expect("In-Memory-DB konnte nicht geoeffnet werden"),
+    );
+    let user = UserRepository::create(
+        db.as_ref(),
+        NeuerBenutzer {
+            username: "erika",
+            password_hash: "hash",
+        },
+    )
+    .await
+    .expect("User anlegen fehlgeschlagen");
+    let kanal = ChannelRepository::create(
+        db.as_ref(),
+        NeuerKanal {
+            name: "Lobby",
+            channel_type: KanalTyp::Text,
+            ..Default::default()
+        },
+    )
+    .await
+    .expect("Kanal anlegen fehlgeschlagen");
+
+    let files = FileService::neu(db.clone(), db, Arc::new(DiskStorage::new(dir)));
+    let (info, _) = files
+        .datei_hochladen(
+            DateiUpload {
+                channel_id: kanal.id,
+                uploader_id: user.id,
+                filename: "notiz.txt".to_string(),
+                mime_type: "text/plain".to_string(),
+                data: b"Hallo Welt".to_vec(),
+            },
+            None,
+        )
+        .await
+        .unwrap();
+    (files, info.id, user.id)
+}
+
+fn token(url: &str) -> &str {
+    url.rsplit('/').next().unwrap()
+}
+
+#[tokio::test]
+async fn test_link_enthaelt_pruefsumme_und_liefert_datei() {
+    let dir = tempfile::tempdir().unwrap();
+    let (files, file_id, user_id) = setup(dir.path()).await;
+    let dienst = DownloadService::neu(files, [7; 32], DownloadKonfig::default());
+
+    let angebot = dienst.link_anfordern(file_id, user_id).await.unwrap();
+    assert!(angebot
+        .download_url
+        .starts_with("http://localhost:9301/files/download/"));
+    assert_eq!(angebot.size_bytes, 10);
+    assert_eq!(angebot.filename, "notiz.txt");
+    assert_eq!(
+        angebot.sha256,
+        format!("{:x}", Sha256::digest(b"Hallo Welt"))
+    );
+
+    // Mehrfach einloesbar bis zum Ablauf
+    for _ in 0..2 {
+        let (info, daten) = dienst
+            .ausliefern(token(&angebot.download_url))
+            .await
+            .unwrap();
+        assert_eq!(info.id, file_id);
+        assert_eq!(daten, b"Hallo Welt");
+    }
+}
+
+#[tokio::test]
+async fn test_abgelaufene_und_veraenderte_links_werden_abgelehnt() {
+    let dir = tempfile::tempdir().unwrap();
+    let (files, file_id, user_id) = setup(dir.path()).await;
+
+    let abgelaufen = DownloadService::neu(
+        Arc::clone(&files),
+        [7; 32],
+        DownloadKonfig {
+            gueltigkeit: Duration::zero(),
+            ..Default::default()
+        },
+    );
+    let angebot = abgelaufen.link_anfordern(file_id, user_id).await.unwrap();
+    assert!(matches!(
+        abgelaufen.ausliefern(token(&angebot.download_url)).await,
+        Err(ChatError::TokenAbgelaufen(_))
+    ));
+
+    let dienst = DownloadService::neu(Arc::clone(&files), [7; 32], DownloadKonfig::default());
+    let gueltig = dienst.link_anfordern(file_id, user_id).await.unwrap();
+    let gueltig = token(&gueltig.download_url);
+
+    // Ablauf des abgelaufenen Links verlaengert, Signatur unveraendert
+    let alt = token(&angebot.download_url);
+    let (nutzdaten, signatur) = alt.rsplit_once('.').unwrap();
+    let (vorne, _) = nutzdaten.rsplit_once('.').unwrap();
+    let verlaengert = format!("{vorne}.{}.{signatur}", i64::MAX);
+
+    let fremder_schluessel = DownloadService::neu(files, [8; 32], DownloadKonfig::default());
+    for falsch in [
+        verlaengert.as_str(),
+        "kaputt",
+        "a.b.c.zz",
+        &gueltig[..gueltig.len() - 2],
+    ] {
+        assert!(
+            matches!(
+                dienst.ausliefern(falsch).await,
+                Err(ChatError::TokenUngueltig(_))
+            ),
+            "{falsch}"
+        );
+    }
+    assert!(matches!(
+        fremder_schluessel.ausliefern(gueltig).await,
+        Err(ChatError::TokenUngueltig(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_geloeschte_datei_ist_nicht_mehr_abrufbar() {
+    let dir = tempfile::tempdir().unwrap();
+    let (files, file_id, user_id) = setup(dir.path()).await;
+    let dienst = DownloadService::neu(Arc::clone(&files), [7; 32], DownloadKonfig::default());
+
+    let angebot = dienst.link_anfordern(file_id, user_id).await.unwrap();
+    files.datei_loeschen(file_id, user_id, None).await.unwrap();
+
+    assert!(matches!(
+        dienst.ausliefern(token(&angebot.download_url)).await,
+        Err(ChatError::DateiNichtGefunden(_))
+    ));
+    assert!(matches!(
+        dienst.link_anfordern(file_id, user_id).await,
+        Err(ChatError::DateiNichtGefunden(_))
+    ));
+}
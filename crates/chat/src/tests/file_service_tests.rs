@@ -3,7 +3,7 @@
 use std::sync::Arc;
 
 use speakeasy_db::models::{KanalTyp, NeuerBenutzer, NeuerKanal};
-use speakeasy_db::{ChannelRepository, SqliteDb, UserRepository};
+use speakeasy_db::{ChannelRepository, FileRepository, SqliteDb, UserRepository};
 use uuid::Uuid;
 
 use crate::{
@@ -103,7 +103,7 @@ async fn test_datei_herunterladen() {
         .unwrap();
 
     let (dl_info, dl_data) = service
-        .datei_herunterladen(info.id)
+        .datei_herunterladen(info.id, uploader_id)
         .await
         .expect("Datei herunterladen fehlgeschlagen");
 
@@ -139,7 +139,7 @@ async fn test_datei_loeschen() {
         .expect("Datei loeschen fehlgeschlagen");
 
     // Nach dem Loeschen sollte die Datei nicht mehr abrufbar sein
-    let result = service.datei_herunterladen(info.id).await;
+    let result = service.datei_herunterladen(info.id, uploader_id).await;
     assert!(matches!(result, Err(ChatError::DateiNichtGefunden(_))));
 }
 
@@ -230,6 +230,72 @@ async fn test_leerer_dateiname_abgelehnt() {
     assert!(matches!(result, Err(ChatError::UngueltigeEingabe(_))));
 }
 
+#[tokio::test]
+async fn test_dateiname_mit_pfad_abgelehnt() {
+    let db = test_db().await;
+    let (channel_id, uploader_id) = setup(&db).await;
+    let (storage, dir) = temp_storage();
+    let service = FileService::neu(db.clone(), db.clone(), Arc::new(storage));
+
+    for name in [
+        "../../../../etc/x",
+        "..",
+        "a/b.txt",
+        "a\\b.txt",
+        "/etc/passwd",
+        "zeile\nbruch.txt",
+    ] {
+        let result = service
+            .datei_hochladen(
+                DateiUpload {
+                    channel_id,
+                    uploader_id,
+                    filename: name.to_string(),
+                    mime_type: "text/plain".to_string(),
+                    data: b"inhalt".to_vec(),
+                },
+                None,
+            )
+            .await;
+        assert!(
+            matches!(result, Err(ChatError::UngueltigeEingabe(_))),
+            "{name:?} nicht abgelehnt"
+        );
+    }
+    // Nichts ausserhalb (und nichts innerhalb) des Speichers angelegt
+    assert!(!dir.path().parent().unwrap().join("etc").exists());
+    assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 0);
+}
+
+#[tokio::test]
+async fn test_speicherpfad_ohne_dateinamen() {
+    let db = test_db().await;
+    let (channel_id, uploader_id) = setup(&db).await;
+    let (storage, _dir) = temp_storage();
+    let service = FileService::neu(db.clone(), db.clone(), Arc::new(storage));
+
+    let (info, _) = service
+        .datei_hochladen(
+            DateiUpload {
+                channel_id,
+                uploader_id,
+                filename: "bericht.pdf".to_string(),
+                mime_type: "application/pdf".to_string(),
+                data: b"inhalt".to_vec(),
+            },
+            None,
+        )
+        .await
+        .unwrap();
+    let record = FileRepository::get_by_id(db.as_ref(), info.id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(record.storage_path.starts_with(&format!("{}/", channel_id)));
+    assert!(!record.storage_path.contains("bericht"));
+    assert_eq!(record.filename, "bericht.pdf");
+}
+
 #[tokio::test]
 async fn test_dateien_auflisten() {
     let db = test_db().await;
@@ -270,6 +336,8 @@ async fn test_nicht_vorhandene_datei_download() {
     let storage = Arc::new(storage);
     let service = FileService::neu(db.clone(), db.clone(), storage);
 
-    let result = service.datei_herunterladen(Uuid::new_v4()).await;
+    let result = service
+        .datei_herunterladen(Uuid::new_v4(), Uuid::new_v4())
+        .await;
     assert!(matches!(result, Err(ChatError::DateiNichtGefunden(_))));
 }
@@ -0,0 +1,141 @@
+//! Unit-Tests fuer den KontoService
+
+use std::path::Path;
+use std::sync::Arc;
+
+use speakeasy_db::models::{KanalTyp, NeuerBenutzer, NeuerKanal};
+use speakeasy_db::{ChannelRepository, SqliteDb, UserRepository};
+use uuid::Uuid;
+
+use crate::{
+    error::ChatError,
+    file_service::FileService,
+    konto_service::{KontoKonfig, KontoService, EXPORT_FORMAT},
+    service::ChatService,
+    storage::DiskStorage,
+    types::DateiUpload,
+};
+
+async fn setup() -> (Arc<SqliteDb>, Uuid, Uuid) {
+    let db = Arc::new(
+        SqliteDb::in_memory()
+            .await
+            .expect("In-Memory-DB konnte nicht geoeffnet werden"),
+    );
+    let user = UserRepository::create(
+        db.as_ref(),
+        NeuerBenutzer {
+            username: "erika",
+            password_hash: "hash",
+        },
+    )
+    .await
+    .expect("User anlegen fehlgeschlagen");
+    let kanal = ChannelRepository::create(
+        db.as_ref(),
+        NeuerKanal {
+            name: "Lobby",
+            channel_type: KanalTyp::Text,
+            ..Default::default()
+        },
+    )
+    .await
+    .expect("Kanal anlegen fehlgeschlagen");
+    (db, kanal.id, user.id)
+}
+
+/// Anzahl der Dateien unterhalb von `dir` (rekursiv)
+fn dateien_zaehlen(dir: &Path) -> usize {
+    std::fs::read_dir(dir)
+        .map(|eintraege| {
+            eintraege
+                .flatten()
+                .map(|e| {
+                    let pfad = e.path();
+                    if pfad.is_dir() {
+                        dateien_zaehlen(&pfad)
+                    } else {
+                        1
+                    }
+                })
+                .sum()
+        })
+        .unwrap_or(0)
+}
+
+#[tokio::test]
+async fn test_export_enthaelt_nachrichten_und_ist_einmalig() {
+    let (db, channel_id, user_id) = setup().await;
+    let dir = tempfile::tempdir().unwrap();
+    let storage = Arc::new(DiskStorage::new(dir.path()));
+    let chat = ChatService::neu(db.clone());
+    chat.nachricht_senden(channel_id, user_id, "Hallo Welt", None)
+        .await
+        .unwrap();
+
+    let service = KontoService::neu(db.clone(), storage, KontoKonfig::default());
+    let auftrag = service.export_anfordern(user_id, true).await.unwrap();
+    let export = service.export_erstellen(&auftrag).await.unwrap();
+    assert!(export
+        .download_url
+        .starts_with("http://localhost:9301/files/export/"));
+    let token = export.download_url.rsplit('/').next().unwrap();
+
+    let archiv = service.export_abholen(token).await.unwrap();
+    assert_eq!(archiv.len() as u64, export.size_bytes);
+    let json: serde_json::Value = serde_json::from_slice(&archiv).unwrap();
+    assert_eq!(json["format"], EXPORT_FORMAT);
+    assert_eq!(json["profil"]["username"], "erika");
+    assert_eq!(json["nachrichten"][0]["content"], "Hallo Welt");
+    assert_eq!(json["nachrichten"][0]["kanal_name"], "Lobby");
+    // Archiv ist nach dem Abholen entfernt
+    assert_eq!(dateien_zaehlen(dir.path()), 0);
+
+    let erneut = service.export_abholen(token).await;
+    assert!(matches!(erneut, Err(ChatError::TokenUngueltig(_))));
+
+    // Einmal pro Sperrfrist, Administratoren sind nicht begrenzt
+    let gesperrt = service.export_anfordern(user_id, true).await;
+    assert!(matches!(
+        gesperrt,
+        Err(ChatError::ZuHaeufig {
+            retry_after_secs: 86_400
+        })
+    ));
+    service.export_anfordern(user_id, false).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_konto_loeschen_entfernt_dateien_und_archive() {
+    let (db, channel_id, user_id) = setup().await;
+    let dir = tempfile::tempdir().unwrap();
+    let storage = Arc::new(DiskStorage::new(dir.path()));
+    let files = FileService::neu(db.clone(), db.clone(), storage.clone());
+    files
+        .datei_hochladen(
+            DateiUpload {
+                channel_id,
+                uploader_id: user_id,
+                filename: "notiz.txt".to_string(),
+                mime_type: "text/plain".to_string(),
+                data: b"privat".to_vec(),
+            },
+            None,
+        )
+        .await
+        .unwrap();
+
+    let service = KontoService::neu(db.clone(), storage, KontoKonfig::default());
+    let auftrag = service.export_anfordern(user_id, true).await.unwrap();
+    service.export_erstellen(&auftrag).await.unwrap();
+    assert_eq!(dateien_zaehlen(dir.path()), 2);
+
+    let loeschung = service.konto_loeschen(user_id, None).await.unwrap();
+    assert_eq!(loeschung.dateien.len(), 1);
+    assert_eq!(loeschung.exporte.len(), 1);
+    assert_eq!(dateien_zaehlen(dir.path()), 0);
+    assert!(UserRepository::get_by_id(db.as_ref(), user_id)
+        .await
+        .unwrap()
+        .is_none());
+}
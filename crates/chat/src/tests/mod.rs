@@ -1,5 +1,9 @@
 //! Tests fuer das Chat-Crate
 
 pub mod chat_service_tests;
+pub mod download_service_tests;
 pub mod file_service_tests;
+pub mod konto_service_tests;
 pub mod storage_tests;
+pub mod upload_service_tests;
+pub mod zugriffs_log_tests;
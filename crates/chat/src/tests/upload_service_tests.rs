@@ -0,0 +1,280 @@
+//! Unit-Tests fuer den UploadService
+
+use std::sync::Arc;
+
+use chrono::Duration;
+use sha2::{Digest, Sha256};
+use speakeasy_db::models::{KanalTyp, NeuerBenutzer, NeuerKanal};
+use speakeasy_db::{ChannelRepository, FileRepository, SqliteDb, UserRepository};
+use uuid::Uuid;
+
+use crate::{
+    error::ChatError,
+    file_service::FileService,
+    storage::{DiskStorage, StorageBackend},
+    types::{NachrichtenTyp, UploadAnmeldung, Zwischendatei},
+    upload_service::{UploadKonfig, UploadService},
+};
+
+type Dateien = FileService<SqliteDb, SqliteDb, DiskStorage>;
+
+/// FileService auf leerer DB; gibt DB, FileService und eine Anmeldung zurueck
+async fn setup(dir: &std::path::Path) -> (Arc<SqliteDb>, Arc<Dateien>, UploadAnmeldung) {
+    let db = Arc::new(
+        SqliteDb::in_memory()
+            .await
+            .expect("In-Memory-DB konnte nicht geoeffnet werden"),
+    );
+    let user = UserRepository::create(
+        db.as_ref(),
+        NeuerBenutzer {
+            username: "erika",
+            password_hash: "hash",
+        },
+    )
+    .await
+    .expect("User anlegen fehlgeschlagen");
+    let kanal = ChannelRepository::create(
+        db.as_ref(),
+        NeuerKanal {
+            name: "Lobby",
+            channel_type: KanalTyp::Text,
+            ..Default::default()
+        },
+    )
+    .await
+    .expect("Kanal anlegen fehlgeschlagen");
+
+    let files = FileService::neu(db.clone(), db.clone(), Arc::new(DiskStorage::new(dir)));
+    let anmeldung = UploadAnmeldung {
+        channel_id: kanal.id,
+        uploader_id: user.id,
+        filename: "notiz.txt".into(),
+        mime_type: "text/plain".into(),
+        size_bytes: 10,
+        checksum: Some(format!("{:x}", Sha256::digest(b"Hallo Welt"))),
+    };
+    (db, files, anmeldung)
+}
+
+fn token(url: &str) -> &str {
+    url.rsplit('/').next().unwrap()
+}
+
+#[tokio::test]
+async fn test_upload_wird_genau_einmal_abgeschlossen() {
+    let dir = tempfile::tempdir().unwrap();
+    let (db, files, anmeldung) = setup(dir.path()).await;
+    let dienst = UploadService::neu(Arc::clone(&files), [7; 32], UploadKonfig::default());
+
+    let angebot = dienst.upload_anfordern(anmeldung).await.unwrap();
+    assert!(angebot
+        .upload_url
+        .starts_with("http://localhost:9301/files/upload/"));
+    let freigabe = dienst.freigabe_pruefen(token(&angebot.upload_url)).unwrap();
+    assert_eq!(freigabe.file_id, angebot.file_id);
+    assert_eq!(freigabe.size_bytes, 10);
+
+    let (info, nachricht) = dienst
+        .hochladen(token(&angebot.upload_url), b"Hallo Welt")
+        .await
+        .unwrap();
+    assert_eq!(info.id, angebot.file_id);
+    assert_eq!(nachricht.message_type, NachrichtenTyp::File);
+    assert_eq!(db.get_quota("default").await.unwrap().current_usage, 10);
+    let record = files.datei_metadaten(angebot.file_id).await.unwrap();
+    assert_eq!(
+        DiskStorage::new(dir.path())
+            .retrieve(&record.storage_path)
+            .await
+            .unwrap(),
+        b"Hallo Welt"
+    );
+
+    // Derselbe Link ein zweites Mal: Anmeldung ist verbraucht
+    assert!(matches!(
+        dienst
+            .hochladen(token(&angebot.upload_url), b"Hallo Welt")
+            .await,
+        Err(ChatError::DateiNichtGefunden(_))
+    ));
+    assert_eq!(db.get_quota("default").await.unwrap().current_usage, 10);
+}
+
+#[tokio::test]
+async fn test_abweichende_groesse_und_pruefsumme_werden_abgelehnt() {
+    let dir = tempfile::tempdir().unwrap();
+    let (_db, files, anmeldung) = setup(dir.path()).await;
+    let channel_id = anmeldung.channel_id;
+    let dienst = UploadService::neu(Arc::clone(&files), [7; 32], UploadKonfig::default());
+    let angebot = dienst.upload_anfordern(anmeldung).await.unwrap();
+    let token = token(&angebot.upload_url);
+
+    assert!(matches!(
+        dienst.hochladen(token, b"Hallo").await,
+        Err(ChatError::GroesseAbweichend {
+            erwartet: 10,
+            erhalten: 5
+        })
+    ));
+    assert!(matches!(
+        dienst.hochladen(token, b"Hallo Welx").await,
+        Err(ChatError::PruefsummeAbweichend)
+    ));
+    assert!(files
+        .dateien_auflisten(channel_id)
+        .await
+        .unwrap()
+        .is_empty());
+
+    // Fehlversuche verbrauchen die Anmeldung nicht
+    dienst.hochladen(token, b"Hallo Welt").await.unwrap();
+    assert_eq!(files.dateien_auflisten(channel_id).await.unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn test_abgelaufene_veraenderte_links_und_kontingent() {
+    let dir = tempfile::tempdir().unwrap();
+    let (db, files, anmeldung) = setup(dir.path()).await;
+
+    let abgelaufen = UploadService::neu(
+        Arc::clone(&files),
+        [7; 32],
+        UploadKonfig {
+            gueltigkeit: Duration::zero(),
+            ..Default::default()
+        },
+    );
+    let angebot = abgelaufen
+        .upload_anfordern(anmeldung.clone())
+        .await
+        .unwrap();
+    assert!(matches!(
+        abgelaufen
+            .hochladen(token(&angebot.upload_url), b"Hallo Welt")
+            .await,
+        Err(ChatError::TokenAbgelaufen(_))
+    ));
+
+    // Groesse im Token veraendert, Signatur unveraendert
+    let dienst = UploadService::neu(Arc::clone(&files), [7; 32], UploadKonfig::default());
+    let angebot = dienst.upload_anfordern(anmeldung.clone()).await.unwrap();
+    let gueltig = token(&angebot.upload_url);
+    let teile: Vec<&str> = gueltig.split('.').collect();
+    let vergroessert = format!("{}.{}.999999.{}.{}", teile[0], teile[1], teile[3], teile[4]);
+    assert!(matches!(
+        dienst.freigabe_pruefen(&vergroessert),
+        Err(ChatError::TokenUngueltig(_))
+    ));
+    assert!(matches!(
+        UploadService::neu(Arc::clone(&files), [8; 32], UploadKonfig::default())
+            .freigabe_pruefen(gueltig),
+        Err(ChatError::TokenUngueltig(_))
+    ));
+
+    // Kontingent seit der Anmeldung von anderen Uploads belegt
+    let kontingent = db.get_quota("default").await.unwrap();
+    db.increment_usage("default", kontingent.max_total_storage - 5)
+        .await
+        .unwrap();
+    assert!(matches!(
+        dienst.hochladen(gueltig, b"Hallo Welt").await,
+        Err(ChatError::KontingentErschoepft { .. })
+    ));
+    // ... und schon bei der Anmeldung
+    assert!(matches!(
+        dienst.upload_anfordern(anmeldung).await,
+        Err(ChatError::KontingentErschoepft { .. })
+    ));
+}
+
+#[tokio::test]
+async fn test_anmeldung_prueft_pruefsummenformat() {
+    let dir = tempfile::tempdir().unwrap();
+    let (_db, files, mut anmeldung) = setup(dir.path()).await;
+    let dienst = UploadService::neu(files, [7; 32], UploadKonfig::default());
+
+    anmeldung.checksum = Some("kein-sha256".into());
+    assert!(matches!(
+        dienst.upload_anfordern(anmeldung).await,
+        Err(ChatError::UngueltigeEingabe(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_anmeldung_lehnt_pfad_im_dateinamen_ab() {
+    let dir = tempfile::tempdir().unwrap();
+    let (_db, files, mut anmeldung) = setup(dir.path()).await;
+    let dienst = UploadService::neu(files, [7; 32], UploadKonfig::default());
+
+    anmeldung.filename = "../../../../etc/x".into();
+    assert!(matches!(
+        dienst.upload_anfordern(anmeldung).await,
+        Err(ChatError::UngueltigeEingabe(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_fremder_benutzer_kann_nicht_abschliessen() {
+    let dir = tempfile::tempdir().unwrap();
+    let (_db, files, anmeldung) = setup(dir.path()).await;
+    let upload = files
+        .upload_anmelden(&anmeldung, chrono::Utc::now() + Duration::minutes(1), None)
+        .await
+        .unwrap();
+
+    assert!(matches!(
+        files
+            .upload_abschliessen(upload.id, Uuid::new_v4(), b"Hallo Welt", None)
+            .await,
+        Err(ChatError::KeineBerechtigung(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_hochladen_aus_zwischendatei_verschiebt_datei() {
+    let dir = tempfile::tempdir().unwrap();
+    let zwischen = tempfile::tempdir().unwrap();
+    let (db, files, anmeldung) = setup(dir.path()).await;
+    let dienst = UploadService::neu(Arc::clone(&files), [7; 32], UploadKonfig::default());
+    let angebot = dienst.upload_anfordern(anmeldung).await.unwrap();
+
+    let pfad = zwischen.path().join("upload.part");
+    std::fs::write(&pfad, b"Hallo Welt").unwrap();
+    let datei = Zwischendatei {
+        pfad: pfad.clone(),
+        size_bytes: 9,
+        sha256: format!("{:x}", Sha256::digest(b"Hallo Welt")),
+    };
+    // Abweichende Groesse: Zwischendatei bleibt unberuehrt
+    assert!(matches!(
+        dienst
+            .hochladen_aus_datei(token(&angebot.upload_url), &datei)
+            .await,
+        Err(ChatError::GroesseAbweichend {
+            erwartet: 10,
+            erhalten: 9
+        })
+    ));
+    assert!(pfad.exists());
+
+    let datei = Zwischendatei {
+        size_bytes: 10,
+        ..datei
+    };
+    let (info, _) = dienst
+        .hochladen_aus_datei(token(&angebot.upload_url), &datei)
+        .await
+        .unwrap();
+    assert_eq!(info.id, angebot.file_id);
+    assert!(!pfad.exists());
+    assert_eq!(db.get_quota("default").await.unwrap().current_usage, 10);
+    let record = files.datei_metadaten(angebot.file_id).await.unwrap();
+    assert_eq!(
+        DiskStorage::new(dir.path())
+            .retrieve(&record.storage_path)
+            .await
+            .unwrap(),
+        b"Hallo Welt"
+    );
+}
@@ -0,0 +1,178 @@
+//! Unit-Tests fuer das Datei-Zugriffsprotokoll
+
+use std::sync::Arc;
+
+use speakeasy_db::models::{DateiZugriffFilter, KanalTyp, NeuerBenutzer, NeuerKanal};
+use speakeasy_db::{ChannelRepository, FileRepository, SqliteDb, UserRepository};
+use uuid::Uuid;
+
+use crate::{
+    file_service::FileService,
+    storage::DiskStorage,
+    types::DateiUpload,
+    zugriffs_log::{ZugriffsLogKonfig, ZugriffsLogModus, ZugriffsLogger},
+};
+
+async fn test_db() -> Arc<SqliteDb> {
+    Arc::new(
+        SqliteDb::in_memory()
+            .await
+            .expect("In-Memory-DB konnte nicht geoeffnet werden"),
+    )
+}
+
+async fn setup(db: &Arc<SqliteDb>) -> (Uuid, Uuid) {
+    let user = UserRepository::create(
+        db.as_ref(),
+        NeuerBenutzer {
+            username: "leser",
+            password_hash: "hash",
+        },
+    )
+    .await
+    .expect("User anlegen fehlgeschlagen");
+
+    let kanal = ChannelRepository::create(
+        db.as_ref(),
+        NeuerKanal {
+            name: "dateikanal",
+            channel_type: KanalTyp::Text,
+            ..Default::default()
+        },
+    )
+    .await
+    .expect("Kanal anlegen fehlgeschlagen");
+
+    (kanal.id, user.id)
+}
+
+fn konfig(modus: ZugriffsLogModus) -> ZugriffsLogKonfig {
+    ZugriffsLogKonfig {
+        modus,
+        ..Default::default()
+    }
+}
+
+/// Laedt eine Datei hoch, laedt sie zweimal herunter und gibt die ID zurueck
+async fn hochladen_und_zweimal_herunterladen(
+    db: &Arc<SqliteDb>,
+    log: Arc<ZugriffsLogger>,
+) -> (Uuid, Uuid, tempfile::TempDir) {
+    let (channel_id, user_id) = setup(db).await;
+    let dir = tempfile::tempdir().expect("Temp-Verzeichnis konnte nicht erstellt werden");
+    let storage = Arc::new(DiskStorage::new(dir.path()));
+    let service = FileService::neu_mit_zugriffs_log(db.clone(), db.clone(), storage, log.clone());
+
+    let (info, _) = service
+        .datei_hochladen(
+            DateiUpload {
+                channel_id,
+                uploader_id: user_id,
+                filename: "geheim.txt".to_string(),
+                mime_type: "text/plain".to_string(),
+                data: b"vertraulich".to_vec(),
+            },
+            None,
+        )
+        .await
+        .unwrap();
+
+    for _ in 0..2 {
+        service.datei_herunterladen(info.id, user_id).await.unwrap();
+    }
+    log.leeren().await;
+
+    (info.id, channel_id, dir)
+}
+
+#[tokio::test]
+async fn test_aggregat_modus_speichert_keine_user_ids() {
+    let db = test_db().await;
+    let log = ZugriffsLogger::starten(db.clone(), konfig(ZugriffsLogModus::Aggregiert));
+    let (file_id, _, _dir) = hochladen_und_zweimal_herunterladen(&db, log).await;
+
+    let eintraege = db
+        .list_access(DateiZugriffFilter {
+            file_id: Some(file_id),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+
+    assert_eq!(eintraege.len(), 2);
+    assert!(eintraege.iter().all(|e| e.user_id.is_none()));
+    assert!(eintraege
+        .iter()
+        .all(|e| e.completed && e.bytes_served == 11));
+}
+
+#[tokio::test]
+async fn test_vollstaendiger_modus_speichert_user_id() {
+    let db = test_db().await;
+    let log = ZugriffsLogger::starten(db.clone(), konfig(ZugriffsLogModus::Vollstaendig));
+    let (file_id, _, _dir) = hochladen_und_zweimal_herunterladen(&db, log).await;
+
+    let eintraege = db
+        .list_access(DateiZugriffFilter {
+            file_id: Some(file_id),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+
+    assert_eq!(eintraege.len(), 2);
+    assert!(eintraege.iter().all(|e| e.user_id.is_some()));
+}
+
+#[tokio::test]
+async fn test_deaktivierter_modus_protokolliert_nichts() {
+    let db = test_db().await;
+    let log = ZugriffsLogger::starten(db.clone(), konfig(ZugriffsLogModus::Deaktiviert));
+    let (file_id, _, _dir) = hochladen_und_zweimal_herunterladen(&db, log).await;
+
+    let anzahl = db
+        .count_access(DateiZugriffFilter {
+            file_id: Some(file_id),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+
+    assert_eq!(anzahl, 0);
+}
+
+#[tokio::test]
+async fn test_dateiliste_enthaelt_zugriffszaehler() {
+    let db = test_db().await;
+    let log = ZugriffsLogger::starten(db.clone(), konfig(ZugriffsLogModus::Aggregiert));
+    let (file_id, channel_id, dir) = hochladen_und_zweimal_herunterladen(&db, log).await;
+
+    let service = FileService::neu(
+        db.clone(),
+        db.clone(),
+        Arc::new(DiskStorage::new(dir.path())),
+    );
+    let liste = service.dateien_auflisten(channel_id).await.unwrap();
+
+    let datei = liste.iter().find(|d| d.id == file_id).unwrap();
+    assert_eq!(datei.zugriffe, 2);
+}
+
+#[tokio::test]
+async fn test_volle_queue_blockiert_nicht() {
+    let db = test_db().await;
+    let log = ZugriffsLogger::starten(
+        db.clone(),
+        ZugriffsLogKonfig {
+            modus: ZugriffsLogModus::Aggregiert,
+            queue_groesse: 1,
+            aufbewahrung_tage: 0,
+        },
+    );
+
+    // Darf auch bei unbekannter Datei und voller Queue nie blockieren
+    for _ in 0..100 {
+        log.protokollieren(Uuid::new_v4(), Uuid::new_v4(), 1, true);
+    }
+    log.leeren().await;
+}
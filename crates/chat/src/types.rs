@@ -1,9 +1,18 @@
 //! Oeffentliche Typen fuer den Chat-Service
 
+use std::path::PathBuf;
+
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+use crate::error::{ChatError, ChatResult};
+
+/// Standard-Trefferzahl einer Suche
+const SUCHE_LIMIT_STANDARD: i64 = 50;
+/// Hoechstens so viele Treffer pro Suchanfrage
+const SUCHE_LIMIT_MAX: i64 = 100;
+
 /// Nachrichtentyp
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
@@ -27,6 +36,37 @@ pub struct ChatNachricht {
     pub edited_at: Option<DateTime<Utc>>,
 }
 
+/// Was ein Benutzer an einer bestehenden Nachricht aendern darf
+///
+/// Der Aufrufer ermittelt die im Kanal geltende Frist (Kanal-Wert oder
+/// Server-Standard) und ob der Benutzer `b_chat_moderate` besitzt.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AenderungsRecht {
+    /// Sekunden nach dem Senden, in denen der Verfasser editieren und
+    /// loeschen darf (`None` = unbegrenzt)
+    pub frist_sek: Option<u32>,
+    /// Moderatoren sind an keine Frist gebunden und duerfen auch fremde
+    /// Nachrichten loeschen
+    pub moderator: bool,
+}
+
+impl AenderungsRecht {
+    /// Prueft die Frist fuer eine um `created_at` gesendete Nachricht
+    ///
+    /// Genau an der Grenze ist die Aenderung noch erlaubt.
+    pub fn pruefen(&self, created_at: DateTime<Utc>, jetzt: DateTime<Utc>) -> ChatResult<()> {
+        let Some(frist_sek) = self.frist_sek.filter(|_| !self.moderator) else {
+            return Ok(());
+        };
+        if jetzt - created_at > chrono::Duration::seconds(i64::from(frist_sek)) {
+            return Err(ChatError::FristAbgelaufen {
+                frist_sek: u64::from(frist_sek),
+            });
+        }
+        Ok(())
+    }
+}
+
 /// Datei-Informationen (fuer Nachrichten vom Typ 'file')
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DateeiInfo {
@@ -34,6 +74,9 @@ pub struct DateeiInfo {
     pub filename: String,
     pub mime_type: String,
     pub size_bytes: i64,
+    /// Anzahl protokollierter Downloads (nur in Kanal-Dateilisten befuellt)
+    #[serde(default)]
+    pub zugriffe: i64,
 }
 
 /// Daten zum Hochladen einer Datei
@@ -46,6 +89,30 @@ pub struct DateiUpload {
     pub data: Vec<u8>,
 }
 
+/// Anmeldung eines Uploads, dessen Inhalt spaeter per HTTP folgt
+#[derive(Debug, Clone)]
+pub struct UploadAnmeldung {
+    pub channel_id: Uuid,
+    pub uploader_id: Uuid,
+    pub filename: String,
+    pub mime_type: String,
+    pub size_bytes: u64,
+    /// SHA-256 (hex), gegen den der Inhalt geprueft wird
+    pub checksum: Option<String>,
+}
+
+/// Beim Empfang in eine Zwischendatei geschriebener Upload-Inhalt
+///
+/// Groesse und SHA-256 hat der Empfaenger beim Schreiben ermittelt; der
+/// Inhalt muss dafuer nicht noch einmal gelesen werden.
+#[derive(Debug, Clone)]
+pub struct Zwischendatei {
+    pub pfad: PathBuf,
+    pub size_bytes: u64,
+    /// SHA-256 des Inhalts (hex, Kleinbuchstaben)
+    pub sha256: String,
+}
+
 /// Cursor-basierte Paginierung fuer die Nachrichten-History
 #[derive(Debug, Clone, Default)]
 pub struct HistoryAnfrage {
@@ -55,3 +122,28 @@ pub struct HistoryAnfrage {
     /// Maximale Anzahl (Default: 50)
     pub limit: Option<i64>,
 }
+
+/// Textsuche in einem Kanal, Treffer neueste zuerst
+#[derive(Debug, Clone, Default)]
+pub struct SuchAnfrage {
+    pub channel_id: Uuid,
+    /// Suchtext; `%`, `_` und `\` werden woertlich gesucht
+    pub query: String,
+    /// Nur Treffer vor diesem Zeitstempel
+    pub before: Option<DateTime<Utc>>,
+    /// Letzter Treffer der Vorseite; zeitgleiche, aeltere Treffer folgen noch
+    pub before_id: Option<Uuid>,
+    /// Maximale Anzahl (Default: 50, hoechstens 100)
+    pub limit: Option<i64>,
+    /// Zu ueberspringende Treffer
+    pub offset: i64,
+}
+
+impl SuchAnfrage {
+    /// Tatsaechliche Trefferzahl pro Seite nach Default und Obergrenze
+    pub fn seitengroesse(&self) -> i64 {
+        self.limit
+            .unwrap_or(SUCHE_LIMIT_STANDARD)
+            .clamp(1, SUCHE_LIMIT_MAX)
+    }
+}
@@ -0,0 +1,324 @@
+//! UploadService – signierte Upload-Links fuer Dateianhaenge
+//!
+//! Der Client meldet einen Upload ueber die Control-Verbindung an
+//! ([`UploadService::upload_anfordern`]) und erhaelt einen zeitlich
+//! begrenzten Link. Den Inhalt sendet er per `PUT` an den Datei-Server,
+//! der ihn mit [`UploadService::hochladen`] abschliesst. Der Token traegt
+//! Datei, Benutzer, angekuendigte Groesse und Ablauf und ist wie beim
+//! [`DownloadService`](crate::DownloadService) mit HMAC-SHA256 signiert:
+//!
+//! ```text
+//! <file_id>.<user_id>.<groesse>.<ablauf_unix>.<signatur_hex>
+//! ```
+//!
+//! Die Groesse im Token erlaubt dem Datei-Server, die Uebertragung
+//! abzubrechen, bevor er mehr liest als angekuendigt. Dateiname und
+//! Pruefsumme stehen in der Upload-Anmeldung der Datenbank; ein Link ist
+//! genau einmal einloesbar, weil der Abschluss die Anmeldung entfernt.
+
+use std::sync::Arc;
+
+use chrono::{DateTime, Duration, Utc};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use uuid::Uuid;
+
+use speakeasy_db::{ChatMessageRepository, Datenbank, FileRepository, SqliteDb};
+
+use crate::{
+    download_service::{hex_dekodieren, DateiFuture},
+    error::{ChatError, ChatResult},
+    file_service::FileService,
+    storage::{DiskStorage, StorageBackend},
+    types::{ChatNachricht, DateeiInfo, UploadAnmeldung, Zwischendatei},
+};
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Konfiguration des UploadService
+#[derive(Debug, Clone)]
+pub struct UploadKonfig {
+    /// Basis-URL fuer Uploads; der Token wird als letztes Segment angehaengt
+    pub basis_url: String,
+    /// Wie lange ein Link gueltig ist
+    pub gueltigkeit: Duration,
+}
+
+impl Default for UploadKonfig {
+    fn default() -> Self {
+        Self {
+            basis_url: "http://localhost:9301/files/upload".into(),
+            gueltigkeit: Duration::minutes(10),
+        }
+    }
+}
+
+/// Upload-Link fuer eine angemeldete Datei
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UploadAngebot {
+    pub file_id: Uuid,
+    pub upload_url: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// Inhalt eines gueltigen Upload-Tokens
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UploadFreigabe {
+    pub file_id: Uuid,
+    pub user_id: Uuid,
+    /// Angekuendigte Groesse in Bytes
+    pub size_bytes: u64,
+}
+
+/// UploadService signiert Upload-Links und schliesst Uploads ab
+pub struct UploadService<F, C, S>
+where
+    F: FileRepository,
+    C: ChatMessageRepository,
+    S: StorageBackend,
+{
+    files: Arc<FileService<F, C, S>>,
+    schluessel: [u8; 32],
+    konfig: UploadKonfig,
+}
+
+impl<F, C, S> UploadService<F, C, S>
+where
+    F: FileRepository,
+    C: ChatMessageRepository,
+    S: StorageBackend,
+{
+    /// Neuen UploadService mit festem Schluessel erstellen
+    pub fn neu(
+        files: Arc<FileService<F, C, S>>,
+        schluessel: [u8; 32],
+        konfig: UploadKonfig,
+    ) -> Arc<Self> {
+        Arc::new(Self {
+            files,
+            schluessel,
+            konfig,
+        })
+    }
+
+    /// Neuen UploadService mit zufaelligem Schluessel erstellen
+    pub fn mit_zufallsschluessel(
+        files: Arc<FileService<F, C, S>>,
+        konfig: UploadKonfig,
+    ) -> Arc<Self> {
+        let mut schluessel = [0u8; 32];
+        schluessel[..16].copy_from_slice(Uuid::new_v4().as_bytes());
+        schluessel[16..].copy_from_slice(Uuid::new_v4().as_bytes());
+        Self::neu(files, schluessel, konfig)
+    }
+
+    /// Meldet einen Upload an und erstellt den Link dafuer
+    ///
+    /// Raeumt dabei abgelaufene Anmeldungen auf.
+    pub async fn upload_anfordern(&self, anmeldung: UploadAnmeldung) -> ChatResult<UploadAngebot> {
+        if let Err(e) = self.files.uploads_aufraeumen().await {
+            tracing::warn!(fehler = %e, "Abgelaufene Upload-Anmeldungen nicht entfernt");
+        }
+
+        let expires_at = Utc::now() + self.konfig.gueltigkeit;
+        let upload = self
+            .files
+            .upload_anmelden(&anmeldung, expires_at, None)
+            .await?;
+        let token = self.token_erstellen(
+            UploadFreigabe {
+                file_id: upload.id,
+                user_id: anmeldung.uploader_id,
+                size_bytes: anmeldung.size_bytes,
+            },
+            expires_at,
+        );
+
+        Ok(UploadAngebot {
+            file_id: upload.id,
+            upload_url: format!("{}/{}", self.konfig.basis_url.trim_end_matches('/'), token),
+            expires_at,
+        })
+    }
+
+    /// Prueft Signatur und Ablauf eines Tokens, ohne die Datenbank zu fragen
+    ///
+    /// Abgelaufene Tokens ergeben [`ChatError::TokenAbgelaufen`], ungueltige
+    /// [`ChatError::TokenUngueltig`].
+    pub fn freigabe_pruefen(&self, token: &str) -> ChatResult<UploadFreigabe> {
+        self.token_pruefen(token, Utc::now())
+    }
+
+    /// Schliesst den Upload zu `token` mit dem uebertragenen Inhalt ab
+    ///
+    /// Neben den Token-Fehlern ergeben abweichende Groesse
+    /// [`ChatError::GroesseAbweichend`], abweichende Pruefsumme
+    /// [`ChatError::PruefsummeAbweichend`] und bereits abgeschlossene
+    /// Uploads [`ChatError::DateiNichtGefunden`].
+    pub async fn hochladen(
+        &self,
+        token: &str,
+        daten: &[u8],
+    ) -> ChatResult<(DateeiInfo, ChatNachricht)> {
+        let freigabe = self.freigabe_pruefen(token)?;
+        if daten.len() as u64 != freigabe.size_bytes {
+            return Err(ChatError::GroesseAbweichend {
+                erwartet: freigabe.size_bytes,
+                erhalten: daten.len() as u64,
+            });
+        }
+        self.files
+            .upload_abschliessen(freigabe.file_id, freigabe.user_id, daten, None)
+            .await
+    }
+
+    /// Wie [`Self::hochladen`], der Inhalt liegt bereits in einer Zwischendatei
+    ///
+    /// Der Datei-Server schreibt den Body beim Empfang dorthin; bei Erfolg
+    /// wird die Zwischendatei in den Storage verschoben.
+    pub async fn hochladen_aus_datei(
+        &self,
+        token: &str,
+        datei: &Zwischendatei,
+    ) -> ChatResult<(DateeiInfo, ChatNachricht)> {
+        let freigabe = self.freigabe_pruefen(token)?;
+        if datei.size_bytes != freigabe.size_bytes {
+            return Err(ChatError::GroesseAbweichend {
+                erwartet: freigabe.size_bytes,
+                erhalten: datei.size_bytes,
+            });
+        }
+        self.files
+            .upload_abschliessen_aus_datei(freigabe.file_id, freigabe.user_id, datei, None)
+            .await
+    }
+
+    fn signatur(&self, nutzdaten: &str) -> HmacSha256 {
+        let mut mac = HmacSha256::new_from_slice(&self.schluessel)
+            .expect("HMAC akzeptiert jede Schluessellaenge");
+        mac.update(nutzdaten.as_bytes());
+        mac
+    }
+
+    fn token_erstellen(&self, freigabe: UploadFreigabe, expires_at: DateTime<Utc>) -> String {
+        let nutzdaten = format!(
+            "{}.{}.{}.{}",
+            freigabe.file_id.simple(),
+            freigabe.user_id.simple(),
+            freigabe.size_bytes,
+            expires_at.timestamp()
+        );
+        let signatur = self.signatur(&nutzdaten).finalize().into_bytes();
+        let hex: String = signatur.iter().map(|b| format!("{b:02x}")).collect();
+        format!("{nutzdaten}.{hex}")
+    }
+
+    fn token_pruefen(&self, token: &str, jetzt: DateTime<Utc>) -> ChatResult<UploadFreigabe> {
+        let ungueltig = || ChatError::TokenUngueltig("Upload-Link ungueltig".into());
+        let (nutzdaten, hex) = token.rsplit_once('.').ok_or_else(ungueltig)?;
+        let signatur = hex_dekodieren(hex).ok_or_else(ungueltig)?;
+        // Vergleich in konstanter Zeit
+        self.signatur(nutzdaten)
+            .verify_slice(&signatur)
+            .map_err(|_| ungueltig())?;
+
+        let mut teile = nutzdaten.split('.');
+        let (Some(file_id), Some(user_id), Some(groesse), Some(ablauf), None) = (
+            teile.next(),
+            teile.next(),
+            teile.next(),
+            teile.next(),
+            teile.next(),
+        ) else {
+            return Err(ungueltig());
+        };
+        let file_id = Uuid::parse_str(file_id).map_err(|_| ungueltig())?;
+        let user_id = Uuid::parse_str(user_id).map_err(|_| ungueltig())?;
+        let size_bytes: u64 = groesse.parse().map_err(|_| ungueltig())?;
+        let ablauf: i64 = ablauf.parse().map_err(|_| ungueltig())?;
+        if jetzt.timestamp() >= ablauf {
+            return Err(ChatError::TokenAbgelaufen(
+                "Upload-Link abgelaufen, bitte neu anfordern".into(),
+            ));
+        }
+        Ok(UploadFreigabe {
+            file_id,
+            user_id,
+            size_bytes,
+        })
+    }
+}
+
+/// Objektsichere Sicht auf den [`UploadService`]
+///
+/// Fuer Signaling und Datei-Server; wie der
+/// [`DateiDienst`](crate::DateiDienst) nur fuer konkrete Typen implementiert.
+pub trait UploadDienst: Send + Sync {
+    fn upload_anfordern(&self, anmeldung: UploadAnmeldung) -> DateiFuture<'_, UploadAngebot>;
+
+    fn freigabe_pruefen(&self, token: &str) -> ChatResult<UploadFreigabe>;
+
+    fn hochladen<'a>(
+        &'a self,
+        token: &'a str,
+        daten: &'a [u8],
+    ) -> DateiFuture<'a, (DateeiInfo, ChatNachricht)>;
+
+    fn hochladen_aus_datei<'a>(
+        &'a self,
+        token: &'a str,
+        datei: &'a Zwischendatei,
+    ) -> DateiFuture<'a, (DateeiInfo, ChatNachricht)>;
+}
+
+impl UploadDienst for UploadService<SqliteDb, SqliteDb, DiskStorage> {
+    fn upload_anfordern(&self, anmeldung: UploadAnmeldung) -> DateiFuture<'_, UploadAngebot> {
+        Box::pin(UploadService::upload_anfordern(self, anmeldung))
+    }
+
+    fn freigabe_pruefen(&self, token: &str) -> ChatResult<UploadFreigabe> {
+        UploadService::freigabe_pruefen(self, token)
+    }
+
+    fn hochladen<'a>(
+        &'a self,
+        token: &'a str,
+        daten: &'a [u8],
+    ) -> DateiFuture<'a, (DateeiInfo, ChatNachricht)> {
+        Box::pin(UploadService::hochladen(self, token, daten))
+    }
+
+    fn hochladen_aus_datei<'a>(
+        &'a self,
+        token: &'a str,
+        datei: &'a Zwischendatei,
+    ) -> DateiFuture<'a, (DateeiInfo, ChatNachricht)> {
+        Box::pin(UploadService::hochladen_aus_datei(self, token, datei))
+    }
+}
+
+impl UploadDienst for UploadService<Datenbank, Datenbank, DiskStorage> {
+    fn upload_anfordern(&self, anmeldung: UploadAnmeldung) -> DateiFuture<'_, UploadAngebot> {
+        Box::pin(UploadService::upload_anfordern(self, anmeldung))
+    }
+
+    fn freigabe_pruefen(&self, token: &str) -> ChatResult<UploadFreigabe> {
+        UploadService::freigabe_pruefen(self, token)
+    }
+
+    fn hochladen<'a>(
+        &'a self,
+        token: &'a str,
+        daten: &'a [u8],
+    ) -> DateiFuture<'a, (DateeiInfo, ChatNachricht)> {
+        Box::pin(UploadService::hochladen(self, token, daten))
+    }
+
+    fn hochladen_aus_datei<'a>(
+        &'a self,
+        token: &'a str,
+        datei: &'a Zwischendatei,
+    ) -> DateiFuture<'a, (DateeiInfo, ChatNachricht)> {
+        Box::pin(UploadService::hochladen_aus_datei(self, token, datei))
+    }
+}
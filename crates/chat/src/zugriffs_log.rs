@@ -0,0 +1,199 @@
+//! Zugriffsprotokoll fuer Datei-Downloads
+//!
+//! Downloads werden nicht synchron in die Datenbank geschrieben, sondern
+//! ueber eine begrenzte Queue an einen eigenen Writer-Thread uebergeben.
+//! Ist die Queue voll, wird der Eintrag verworfen statt den Download zu
+//! blockieren. Im Aggregat-Modus wird die Benutzer-ID bereits vor dem
+//! Einreihen entfernt, sodass sie den Prozess nie in Richtung DB verlaesst.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::{mpsc, oneshot};
+use uuid::Uuid;
+
+use speakeasy_db::{models::NeuerDateiZugriff, FileRepository};
+
+/// Intervall fuer die Bereinigung alter Protokolleintraege: 1 Stunde
+const BEREINIGUNGS_INTERVALL: Duration = Duration::from_secs(60 * 60);
+
+/// Protokollierungs-Modus fuer Datei-Zugriffe
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ZugriffsLogModus {
+    /// Keine Protokollierung
+    Deaktiviert,
+    /// Nur Zaehler pro Datei, ohne Benutzer-ID
+    Aggregiert,
+    /// Vollstaendige Protokollierung inkl. Benutzer-ID
+    #[default]
+    Vollstaendig,
+}
+
+/// Konfiguration des Zugriffsprotokolls
+#[derive(Debug, Clone)]
+pub struct ZugriffsLogKonfig {
+    pub modus: ZugriffsLogModus,
+    /// Maximale Anzahl wartender Eintraege in der Queue
+    pub queue_groesse: usize,
+    /// Aufbewahrungsdauer in Tagen (0 = unbegrenzt)
+    pub aufbewahrung_tage: u32,
+}
+
+impl Default for ZugriffsLogKonfig {
+    fn default() -> Self {
+        Self {
+            modus: ZugriffsLogModus::Vollstaendig,
+            queue_groesse: 1024,
+            aufbewahrung_tage: 90,
+        }
+    }
+}
+
+/// Nachricht an den Writer-Thread
+enum WriterNachricht {
+    Eintrag(NeuerDateiZugriff),
+    /// Bestaetigt, sobald alle vorherigen Eintraege geschrieben wurden
+    Leeren(oneshot::Sender<()>),
+}
+
+/// Gepufferter Writer fuer das Datei-Zugriffsprotokoll
+pub struct ZugriffsLogger {
+    modus: ZugriffsLogModus,
+    tx: Option<mpsc::Sender<WriterNachricht>>,
+}
+
+impl ZugriffsLogger {
+    /// Startet den Writer-Thread und gibt den Logger zurueck
+    ///
+    /// Muss innerhalb einer tokio-Runtime aufgerufen werden.
+    /// Im Modus `Deaktiviert` wird kein Thread gestartet.
+    pub fn starten<F>(file_repo: Arc<F>, konfig: ZugriffsLogKonfig) -> Arc<Self>
+    where
+        F: FileRepository + 'static,
+    {
+        if konfig.modus == ZugriffsLogModus::Deaktiviert {
+            return Arc::new(Self {
+                modus: konfig.modus,
+                tx: None,
+            });
+        }
+
+        let (tx, rx) = mpsc::channel(konfig.queue_groesse.max(1));
+        let aufbewahrung_tage = konfig.aufbewahrung_tage;
+
+        // Eigener Thread auf der aktuellen Runtime, da die Repository-Futures
+        // (async_fn_in_trait) keine Send-Garantie fuer tokio::spawn bieten.
+        let handle = tokio::runtime::Handle::current();
+        let gestartet = std::thread::Builder::new()
+            .name("datei-zugriffslog".into())
+            .spawn(move || {
+                handle.block_on(writer_loop(file_repo, rx, aufbewahrung_tage));
+            });
+
+        let tx = match gestartet {
+            Ok(_) => Some(tx),
+            Err(e) => {
+                tracing::error!(%e, "Writer-Thread fuer Zugriffsprotokoll nicht gestartet");
+                None
+            }
+        };
+
+        Arc::new(Self {
+            modus: konfig.modus,
+            tx,
+        })
+    }
+
+    /// Gibt den konfigurierten Modus zurueck
+    pub fn modus(&self) -> ZugriffsLogModus {
+        self.modus
+    }
+
+    /// Reiht einen Download-Zugriff ein, ohne zu blockieren
+    ///
+    /// Im Aggregat-Modus wird die Benutzer-ID verworfen.
+    pub fn protokollieren(&self, file_id: Uuid, user_id: Uuid, bytes_served: i64, completed: bool) {
+        let Some(tx) = &self.tx else {
+            return;
+        };
+
+        let user_id = match self.modus {
+            ZugriffsLogModus::Vollstaendig => Some(user_id),
+            ZugriffsLogModus::Aggregiert | ZugriffsLogModus::Deaktiviert => None,
+        };
+
+        let eintrag = NeuerDateiZugriff {
+            file_id,
+            user_id,
+            bytes_served,
+            completed,
+        };
+
+        if let Err(e) = tx.try_send(WriterNachricht::Eintrag(eintrag)) {
+            match e {
+                mpsc::error::TrySendError::Full(_) => {
+                    tracing::warn!(file_id = %file_id, "Zugriffsprotokoll-Queue voll, Eintrag verworfen");
+                }
+                mpsc::error::TrySendError::Closed(_) => {
+                    tracing::warn!(file_id = %file_id, "Zugriffsprotokoll-Writer beendet");
+                }
+            }
+        }
+    }
+
+    /// Wartet bis alle bisher eingereihten Eintraege geschrieben wurden
+    pub async fn leeren(&self) {
+        let Some(tx) = &self.tx else {
+            return;
+        };
+        let (bestaetigung_tx, bestaetigung_rx) = oneshot::channel();
+        if tx
+            .send(WriterNachricht::Leeren(bestaetigung_tx))
+            .await
+            .is_ok()
+        {
+            let _ = bestaetigung_rx.await;
+        }
+    }
+}
+
+/// Schreibt eingereihte Eintraege und bereinigt periodisch alte Eintraege
+async fn writer_loop<F: FileRepository>(
+    file_repo: Arc<F>,
+    mut rx: mpsc::Receiver<WriterNachricht>,
+    aufbewahrung_tage: u32,
+) {
+    let mut bereinigung = tokio::time::interval(BEREINIGUNGS_INTERVALL);
+
+    loop {
+        tokio::select! {
+            nachricht = rx.recv() => match nachricht {
+                Some(WriterNachricht::Eintrag(eintrag)) => {
+                    if let Err(e) = file_repo.log_access(eintrag).await {
+                        tracing::error!(%e, "Zugriffsprotokoll-Eintrag konnte nicht geschrieben werden");
+                    }
+                }
+                Some(WriterNachricht::Leeren(bestaetigung)) => {
+                    let _ = bestaetigung.send(());
+                }
+                None => break,
+            },
+            _ = bereinigung.tick(), if aufbewahrung_tage > 0 => {
+                let grenze = chrono::Utc::now() - chrono::Duration::days(aufbewahrung_tage as i64);
+                match file_repo.purge_access_before(grenze).await {
+                    Ok(anzahl) if anzahl > 0 => {
+                        tracing::info!(anzahl, "Alte Zugriffsprotokoll-Eintraege bereinigt");
+                    }
+                    Ok(_) => {}
+                    Err(e) => {
+                        tracing::error!(%e, "Fehler beim Bereinigen des Zugriffsprotokolls");
+                    }
+                }
+            }
+        }
+    }
+
+    tracing::debug!("Zugriffsprotokoll-Writer beendet");
+}
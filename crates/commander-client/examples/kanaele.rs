@@ -0,0 +1,49 @@
+//! Listet die Kanaele, legt einen Kanal an und loescht ihn wieder
+//!
+//! ```text
+//! SE_COMMANDER_URL=http://127.0.0.1:9300 SE_COMMANDER_TOKEN=<token> \
+//!     cargo run -p speakeasy-commander-client --example kanaele
+//! ```
+//!
+//! Fuer mTLS zusaetzlich `SE_COMMANDER_CA`, `SE_COMMANDER_ZERT` und
+//! `SE_COMMANDER_SCHLUESSEL` (PEM-Dateien) setzen.
+
+use std::path::Path;
+
+use speakeasy_commander_client::typen::KanalErstellenBody;
+use speakeasy_commander_client::{ClientResult, ClientTls, CommanderClient};
+
+#[tokio::main]
+async fn main() -> ClientResult<()> {
+    let url = std::env::var("SE_COMMANDER_URL").unwrap_or_else(|_| "http://127.0.0.1:9300".into());
+    let mut client = CommanderClient::neu(&url)?;
+    if let Ok(token) = std::env::var("SE_COMMANDER_TOKEN") {
+        client = client.mit_token(token);
+    }
+    if let Ok(ca) = std::env::var("SE_COMMANDER_CA") {
+        let zert = std::env::var("SE_COMMANDER_ZERT").ok();
+        let schluessel = std::env::var("SE_COMMANDER_SCHLUESSEL").ok();
+        let client_zertifikat = zert
+            .as_deref()
+            .zip(schluessel.as_deref())
+            .map(|(zert, schluessel)| (Path::new(zert), Path::new(schluessel)));
+        client = client.mit_tls(ClientTls::laden(Some(Path::new(&ca)), client_zertifikat)?)?;
+    }
+
+    for kanal in client.kanaele().await? {
+        println!("{}  {}", kanal.id, kanal.name);
+    }
+
+    let kanal = client
+        .kanal_erstellen(&KanalErstellenBody {
+            name: "Beispielkanal".into(),
+            thema: Some("Angelegt vom Commander-Client".into()),
+            ..Default::default()
+        })
+        .await?;
+    println!("angelegt: {}  {}", kanal.id, kanal.name);
+
+    client.kanal_loeschen(kanal.id).await?;
+    println!("geloescht: {}", kanal.id);
+    Ok(())
+}
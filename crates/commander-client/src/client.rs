@@ -0,0 +1,425 @@
+//! Verbindung zum Commander-REST-Server
+//!
+//! Jede Anfrage oeffnet eine eigene HTTP/1.1-Verbindung (optional ueber
+//! TLS). Authentifiziert wird per Bearer-Token, per Client-Zertifikat
+//! (mTLS) oder beidem.
+
+use std::{path::Path, sync::Arc, time::Duration};
+
+use bytes::Bytes;
+use http_body_util::{BodyExt, Full};
+use hyper::{header, Method, Request, StatusCode};
+use hyper_util::rt::TokioIo;
+use rustls::pki_types::{CertificateDer, PrivateKeyDer, ServerName};
+use rustls::{ClientConfig, RootCertStore};
+use serde::{de::DeserializeOwned, Serialize};
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::net::TcpStream;
+use tokio_rustls::TlsConnector;
+
+use crate::fehler::{ClientFehler, ClientResult};
+
+/// Standard-Zeitlimit pro Anfrage (Verbindungsaufbau bis Antwortende)
+pub const STANDARD_ZEITLIMIT: Duration = Duration::from_secs(30);
+
+/// TLS-Einstellungen des Clients
+#[derive(Clone)]
+pub struct ClientTls {
+    config: Arc<ClientConfig>,
+}
+
+impl ClientTls {
+    /// Laedt Vertrauensanker und optional ein Client-Zertifikat (PEM)
+    ///
+    /// Ohne `ca_pfad` gelten die Web-PKI-Wurzeln. `client_zertifikat` ist
+    /// `(zertifikatskette, privater_schluessel)` fuer mTLS.
+    pub fn laden(
+        ca_pfad: Option<&Path>,
+        client_zertifikat: Option<(&Path, &Path)>,
+    ) -> ClientResult<Self> {
+        let mut wurzeln = RootCertStore::empty();
+        match ca_pfad {
+            Some(pfad) => {
+                for zertifikat in zertifikate_laden(pfad)? {
+                    wurzeln
+                        .add(zertifikat)
+                        .map_err(|e| ClientFehler::Tls(format!("{}: {e}", pfad.display())))?;
+                }
+            }
+            None => wurzeln.extend(webpki_roots::TLS_SERVER_ROOTS.iter().cloned()),
+        }
+
+        let builder =
+            ClientConfig::builder_with_provider(Arc::new(rustls::crypto::ring::default_provider()))
+                .with_safe_default_protocol_versions()
+                .map_err(|e| ClientFehler::Tls(e.to_string()))?
+                .with_root_certificates(wurzeln);
+        let config = match client_zertifikat {
+            Some((kette_pfad, schluessel_pfad)) => builder
+                .with_client_auth_cert(
+                    zertifikate_laden(kette_pfad)?,
+                    schluessel_laden(schluessel_pfad)?,
+                )
+                .map_err(|e| ClientFehler::Tls(e.to_string()))?,
+            None => builder.with_no_client_auth(),
+        };
+        Ok(Self::aus_konfig(Arc::new(config)))
+    }
+
+    /// Verwendet eine fertige rustls-Konfiguration
+    pub fn aus_konfig(config: Arc<ClientConfig>) -> Self {
+        Self { config }
+    }
+}
+
+impl std::fmt::Debug for ClientTls {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ClientTls").finish_non_exhaustive()
+    }
+}
+
+fn pem_lesen(pfad: &Path) -> ClientResult<Vec<u8>> {
+    std::fs::read(pfad).map_err(|e| ClientFehler::Tls(format!("{}: {e}", pfad.display())))
+}
+
+fn zertifikate_laden(pfad: &Path) -> ClientResult<Vec<CertificateDer<'static>>> {
+    let pem = pem_lesen(pfad)?;
+    let zertifikate = rustls_pemfile::certs(&mut pem.as_slice())
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| ClientFehler::Tls(format!("{}: {e}", pfad.display())))?;
+    if zertifikate.is_empty() {
+        return Err(ClientFehler::Tls(format!(
+            "{}: keine Zertifikate gefunden",
+            pfad.display()
+        )));
+    }
+    Ok(zertifikate)
+}
+
+fn schluessel_laden(pfad: &Path) -> ClientResult<PrivateKeyDer<'static>> {
+    let pem = pem_lesen(pfad)?;
+    rustls_pemfile::private_key(&mut pem.as_slice())
+        .map_err(|e| ClientFehler::Tls(format!("{}: {e}", pfad.display())))?
+        .ok_or_else(|| ClientFehler::Tls(format!("{}: kein privater Schluessel", pfad.display())))
+}
+
+/// Zerlegte Basis-URL
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Ziel {
+    https: bool,
+    /// Hostname oder IP ohne Klammern (fuer Verbindung und SNI)
+    host: String,
+    port: u16,
+    /// Pfad-Praefix ohne abschliessenden `/` (z.B. hinter einem Reverse-Proxy)
+    praefix: String,
+}
+
+impl Ziel {
+    fn parsen(url: &str) -> ClientResult<Self> {
+        let ungueltig = || ClientFehler::Adresse(url.to_string());
+        let (https, rest) = if let Some(rest) = url.strip_prefix("https://") {
+            (true, rest)
+        } else if let Some(rest) = url.strip_prefix("http://") {
+            (false, rest)
+        } else {
+            return Err(ungueltig());
+        };
+        let (autoritaet, praefix) = match rest.find('/') {
+            Some(i) => (&rest[..i], rest[i..].trim_end_matches('/')),
+            None => (rest, ""),
+        };
+
+        let (host, port) = if let Some(v6) = autoritaet.strip_prefix('[') {
+            let (host, rest) = v6.split_once(']').ok_or_else(ungueltig)?;
+            (host, rest.strip_prefix(':'))
+        } else {
+            match autoritaet.rsplit_once(':') {
+                Some((host, port)) => (host, Some(port)),
+                None => (autoritaet, None),
+            }
+        };
+        if host.is_empty() {
+            return Err(ungueltig());
+        }
+        let port = match port {
+            Some(port) => port.parse().map_err(|_| ungueltig())?,
+            None if https => 443,
+            None => 80,
+        };
+        Ok(Self {
+            https,
+            host: host.to_string(),
+            port,
+            praefix: praefix.to_string(),
+        })
+    }
+
+    /// Wert des `Host`-Headers
+    fn host_header(&self) -> String {
+        let host = if self.host.contains(':') {
+            format!("[{}]", self.host)
+        } else {
+            self.host.clone()
+        };
+        match (self.https, self.port) {
+            (true, 443) | (false, 80) => host,
+            (_, port) => format!("{host}:{port}"),
+        }
+    }
+}
+
+/// Typisierter Client fuer die Commander-REST-API (`/v1/...`)
+///
+/// ```no_run
+/// # async fn beispiel() -> speakeasy_commander_client::ClientResult<()> {
+/// use speakeasy_commander_client::CommanderClient;
+///
+/// let client = CommanderClient::neu("http://127.0.0.1:9300")?.mit_token("sitzungs-token");
+/// for kanal in client.kanaele().await? {
+///     println!("{} {}", kanal.id, kanal.name);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct CommanderClient {
+    ziel: Ziel,
+    token: Option<String>,
+    tls: Option<ClientTls>,
+    zeitlimit: Duration,
+}
+
+impl CommanderClient {
+    /// Client fuer `http://host:port` bzw. `https://host:port[/praefix]`
+    ///
+    /// Bei `https` ohne [`mit_tls`](Self::mit_tls) gelten die Web-PKI-Wurzeln.
+    pub fn neu(basis_url: &str) -> ClientResult<Self> {
+        let ziel = Ziel::parsen(basis_url)?;
+        let tls = if ziel.https {
+            Some(ClientTls::laden(None, None)?)
+        } else {
+            None
+        };
+        Ok(Self {
+            ziel,
+            token: None,
+            tls,
+            zeitlimit: STANDARD_ZEITLIMIT,
+        })
+    }
+
+    /// Authentifiziert jede Anfrage mit `Authorization: Bearer <token>`
+    pub fn mit_token(mut self, token: impl Into<String>) -> Self {
+        self.token = Some(token.into());
+        self
+    }
+
+    /// Eigene Vertrauensanker bzw. Client-Zertifikat (nur fuer `https`)
+    pub fn mit_tls(mut self, tls: ClientTls) -> ClientResult<Self> {
+        if !self.ziel.https {
+            return Err(ClientFehler::Adresse(
+                "TLS-Einstellungen erfordern eine https-URL".into(),
+            ));
+        }
+        self.tls = Some(tls);
+        Ok(self)
+    }
+
+    /// Zeitlimit pro Anfrage (Standard: [`STANDARD_ZEITLIMIT`])
+    pub fn mit_zeitlimit(mut self, zeitlimit: Duration) -> Self {
+        self.zeitlimit = zeitlimit;
+        self
+    }
+
+    /// Sendet eine Anfrage und deserialisiert die JSON-Antwort
+    pub(crate) async fn json<T: DeserializeOwned>(
+        &self,
+        methode: Method,
+        pfad: &str,
+        rumpf: Option<&(impl Serialize + ?Sized)>,
+    ) -> ClientResult<T> {
+        let antwort = self.anfrage(methode, pfad, rumpf).await?;
+        serde_json::from_slice(&antwort).map_err(|e| ClientFehler::Antwort(e.to_string()))
+    }
+
+    /// Sendet eine Anfrage, deren Antwort keinen Inhalt traegt
+    pub(crate) async fn ohne_antwort(
+        &self,
+        methode: Method,
+        pfad: &str,
+        rumpf: Option<&(impl Serialize + ?Sized)>,
+    ) -> ClientResult<()> {
+        self.anfrage(methode, pfad, rumpf).await.map(drop)
+    }
+
+    async fn anfrage(
+        &self,
+        methode: Method,
+        pfad: &str,
+        rumpf: Option<&(impl Serialize + ?Sized)>,
+    ) -> ClientResult<Bytes> {
+        let mut anfrage = Request::builder()
+            .method(methode)
+            .uri(format!("{}{pfad}", self.ziel.praefix))
+            .header(header::HOST, self.ziel.host_header())
+            .header(header::ACCEPT, "application/json");
+        if let Some(token) = &self.token {
+            anfrage = anfrage.header(header::AUTHORIZATION, format!("Bearer {token}"));
+        }
+        let inhalt = match rumpf {
+            Some(rumpf) => {
+                anfrage = anfrage.header(header::CONTENT_TYPE, "application/json");
+                serde_json::to_vec(rumpf).map_err(|e| ClientFehler::Anfrage(e.to_string()))?
+            }
+            None => Vec::new(),
+        };
+        let anfrage = anfrage
+            .body(Full::new(Bytes::from(inhalt)))
+            .map_err(|e| ClientFehler::Anfrage(e.to_string()))?;
+
+        let (status, antwort) = tokio::time::timeout(self.zeitlimit, self.senden(anfrage))
+            .await
+            .map_err(|_| ClientFehler::Zeitlimit(self.zeitlimit))??;
+        if status.is_client_error() || status.is_server_error() {
+            return Err(ClientFehler::aus_antwort(status.as_u16(), &antwort));
+        }
+        Ok(antwort)
+    }
+
+    async fn senden(&self, anfrage: Request<Full<Bytes>>) -> ClientResult<(StatusCode, Bytes)> {
+        let tcp = TcpStream::connect((self.ziel.host.as_str(), self.ziel.port))
+            .await
+            .map_err(|e| ClientFehler::Verbindung(e.to_string()))?;
+        let _ = tcp.set_nodelay(true);
+        match &self.tls {
+            Some(tls) => {
+                let name = ServerName::try_from(self.ziel.host.clone())
+                    .map_err(|e| ClientFehler::Adresse(e.to_string()))?;
+                let stream = TlsConnector::from(Arc::clone(&tls.config))
+                    .connect(name, tcp)
+                    .await
+                    .map_err(|e| ClientFehler::Tls(e.to_string()))?;
+                austauschen(stream, anfrage).await
+            }
+            None => austauschen(tcp, anfrage).await,
+        }
+    }
+}
+
+/// Fuehrt genau einen Anfrage/Antwort-Austausch ueber `stream` aus
+async fn austauschen<S>(
+    stream: S,
+    anfrage: Request<Full<Bytes>>,
+) -> ClientResult<(StatusCode, Bytes)>
+where
+    S: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+{
+    let verbindungsfehler = |e: hyper::Error| ClientFehler::Verbindung(e.to_string());
+    let (mut sender, verbindung) = hyper::client::conn::http1::handshake(TokioIo::new(stream))
+        .await
+        .map_err(verbindungsfehler)?;
+    tokio::spawn(async move {
+        let _ = verbindung.await;
+    });
+    let antwort = sender
+        .send_request(anfrage)
+        .await
+        .map_err(verbindungsfehler)?;
+    let status = antwort.status();
+    let rumpf = antwort
+        .into_body()
+        .collect()
+        .await
+        .map_err(verbindungsfehler)?
+        .to_bytes();
+    Ok((status, rumpf))
+}
+
+/// Kodiert einen Pfadabschnitt (alles ausser RFC-3986-"unreserved")
+pub(crate) fn segment(wert: &str) -> String {
+    let mut kodiert = String::with_capacity(wert.len());
+    for byte in wert.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                kodiert.push(byte as char)
+            }
+            _ => kodiert.push_str(&format!("%{byte:02X}")),
+        }
+    }
+    kodiert
+}
+
+/// Haengt die Query-Parameter an (`None`-Felder entfallen)
+pub(crate) fn mit_query(pfad: &str, query: &impl Serialize) -> ClientResult<String> {
+    let query =
+        serde_urlencoded::to_string(query).map_err(|e| ClientFehler::Anfrage(e.to_string()))?;
+    if query.is_empty() {
+        Ok(pfad.to_string())
+    } else {
+        Ok(format!("{pfad}?{query}"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use speakeasy_commander::rest::typen::LogQuery;
+
+    #[test]
+    fn basis_url_wird_zerlegt() {
+        let ziel = Ziel::parsen("https://commander.example.org/api/").unwrap();
+        assert_eq!(
+            ziel,
+            Ziel {
+                https: true,
+                host: "commander.example.org".into(),
+                port: 443,
+                praefix: "/api".into(),
+            }
+        );
+        assert_eq!(ziel.host_header(), "commander.example.org");
+
+        let ziel = Ziel::parsen("http://[::1]:9300").unwrap();
+        assert_eq!((ziel.host.as_str(), ziel.port), ("::1", 9300));
+        assert_eq!(ziel.host_header(), "[::1]:9300");
+
+        assert!(Ziel::parsen("ftp://host").is_err());
+        assert!(Ziel::parsen("http://:9300").is_err());
+        assert!(Ziel::parsen("http://host:port").is_err());
+    }
+
+    #[test]
+    fn pfad_und_query_werden_kodiert() {
+        assert_eq!(segment("user:1 a/b"), "user%3A1%20a%2Fb");
+        let query = LogQuery {
+            limit: Some(10),
+            offset: None,
+            aktion: Some("kanal.erstellt".into()),
+        };
+        assert_eq!(
+            mit_query("/v1/logs", &query).unwrap(),
+            "/v1/logs?limit=10&aktion=kanal.erstellt"
+        );
+        assert_eq!(
+            mit_query("/v1/logs", &LogQuery::default()).unwrap(),
+            "/v1/logs"
+        );
+    }
+
+    #[test]
+    fn tls_nur_fuer_https() {
+        let tls = ClientTls::laden(None, None).unwrap();
+        assert!(CommanderClient::neu("http://127.0.0.1:9300")
+            .unwrap()
+            .mit_tls(tls.clone())
+            .is_err());
+        assert!(CommanderClient::neu("https://127.0.0.1:9300")
+            .unwrap()
+            .mit_tls(tls)
+            .is_ok());
+        assert!(matches!(
+            ClientTls::laden(Some(Path::new("/nicht/vorhanden.pem")), None),
+            Err(ClientFehler::Tls(_))
+        ));
+    }
+}
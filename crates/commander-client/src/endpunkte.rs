@@ -0,0 +1,547 @@
+//! Typisierte Methoden fuer alle `/v1`-Endpunkte
+//!
+//! Anfrage- und Antworttypen stammen aus
+//! [`speakeasy_commander::rest::typen`], die Handler des Servers nutzen
+//! dieselben Definitionen.
+
+use hyper::Method;
+use uuid::Uuid;
+
+use speakeasy_commander::rest::typen::{
+    AlarmStatus, BackupBody, BackupGestartet, BanBody, BenutzerInfo, BenutzerQuery, BenutzerSeite,
+    BerechtigungsEintrag, BereinigungBody, BereinigungsErgebnis, ClientInfo, ClientQuery,
+    ClientSeite, DateiEintrag, DateiZugriffEintrag, DateiZugriffSeite, DeaktivierenBody,
+    EffektivQuery, EffektiverBerechtigungsEintrag, HistorieQuery, InstanziierenBody,
+    KanalBearbeitenBody, KanalErstellenBody, KanalInfo, KanalQuery, KanalSeite, KickBody,
+    KontoExportErgebnis, KontoLoeschErgebnis, LogEintrag, LogQuery, Motd, MotdBody, MoveAllBody,
+    MoveBody, NotfallStummBody, NotfallStummErgebnis, PasswortSetzenBody, PokeBody,
+    RemovePermissionBody, SammelVerschiebungErgebnis, ServerBearbeitenBody, ServerInfoResponse,
+    ServerStartInfo, ServerStoppenBody, SetPermissionBody, SoundInfo, SoundQuery,
+    SoundRegistrierenBody, VorlageErstellenBody, VorlageInfo, ZeitplanErstellenBody, ZeitplanInfo,
+    ZugriffsQuery,
+};
+
+use crate::client::{mit_query, segment, CommanderClient};
+use crate::fehler::ClientResult;
+use crate::seiten::{Seiten, MAX_SEITENGROESSE};
+
+/// Platzhalter fuer Anfragen ohne Rumpf
+const KEIN_RUMPF: Option<&()> = None;
+
+impl CommanderClient {
+    // -----------------------------------------------------------------------
+    // Server
+    // -----------------------------------------------------------------------
+
+    /// `GET /v1/server`
+    pub async fn server_info(&self) -> ClientResult<ServerInfoResponse> {
+        self.json(Method::GET, "/v1/server", KEIN_RUMPF).await
+    }
+
+    /// `GET /v1/server/history` (neueste Starts zuerst)
+    pub async fn server_historie(&self, limit: Option<u32>) -> ClientResult<Vec<ServerStartInfo>> {
+        let pfad = mit_query("/v1/server/history", &HistorieQuery { limit })?;
+        self.json(Method::GET, &pfad, KEIN_RUMPF).await
+    }
+
+    /// `PUT /v1/server`
+    pub async fn server_bearbeiten(&self, aenderung: &ServerBearbeitenBody) -> ClientResult<()> {
+        self.ohne_antwort(Method::PUT, "/v1/server", Some(aenderung))
+            .await
+    }
+
+    /// `POST /v1/server/stop`
+    pub async fn server_stoppen(&self, anfrage: &ServerStoppenBody) -> ClientResult<()> {
+        self.ohne_antwort(Method::POST, "/v1/server/stop", Some(anfrage))
+            .await
+    }
+
+    /// `POST /v1/server/backup` (Fortschritt folgt als Ereignis)
+    pub async fn sicherung_erstellen(&self, anfrage: &BackupBody) -> ClientResult<BackupGestartet> {
+        self.json(Method::POST, "/v1/server/backup", Some(anfrage))
+            .await
+    }
+
+    /// `PUT /v1/server/motd` (leerer Text entfernt die Nachricht)
+    pub async fn motd_setzen(&self, markdown: &str) -> ClientResult<Motd> {
+        let anfrage = MotdBody {
+            markdown: markdown.to_string(),
+        };
+        self.json(Method::PUT, "/v1/server/motd", Some(&anfrage))
+            .await
+    }
+
+    /// `POST /v1/server/alerts/test` (wertet alle Alarmregeln sofort aus)
+    pub async fn alarm_regeln_testen(&self) -> ClientResult<Vec<AlarmStatus>> {
+        self.json(Method::POST, "/v1/server/alerts/test", KEIN_RUMPF)
+            .await
+    }
+
+    // -----------------------------------------------------------------------
+    // Kanaele
+    // -----------------------------------------------------------------------
+
+    /// Alle Kanaele (laedt saemtliche Seiten von `GET /v1/channels`)
+    pub async fn kanaele(&self) -> ClientResult<Vec<KanalInfo>> {
+        self.kanaele_seitenweise(KanalQuery::default(), MAX_SEITENGROESSE)
+            .alle()
+            .await
+    }
+
+    /// `GET /v1/channels` (eine Seite)
+    pub async fn kanal_seite(&self, filter: &KanalQuery) -> ClientResult<KanalSeite> {
+        let pfad = mit_query("/v1/channels", filter)?;
+        self.json(Method::GET, &pfad, KEIN_RUMPF).await
+    }
+
+    /// Kanalliste seitenweise (`page`/`page_size` aus `filter` entfallen)
+    pub fn kanaele_seitenweise(
+        &self,
+        filter: KanalQuery,
+        seitengroesse: u32,
+    ) -> Seiten<'_, KanalInfo> {
+        Seiten::neu(seitengroesse, move |limit, offset| {
+            let filter = KanalQuery {
+                page: Some(offset / limit + 1),
+                page_size: Some(limit),
+                ..filter.clone()
+            };
+            Box::pin(async move {
+                let seite = self.kanal_seite(&filter).await?;
+                Ok((seite.kanaele, Some(seite.seite.total_count)))
+            })
+        })
+    }
+
+    /// `POST /v1/channels`
+    pub async fn kanal_erstellen(&self, kanal: &KanalErstellenBody) -> ClientResult<KanalInfo> {
+        self.json(Method::POST, "/v1/channels", Some(kanal)).await
+    }
+
+    /// `PUT /v1/channels/:id`
+    pub async fn kanal_bearbeiten(
+        &self,
+        id: Uuid,
+        aenderung: &KanalBearbeitenBody,
+    ) -> ClientResult<KanalInfo> {
+        self.json(Method::PUT, &format!("/v1/channels/{id}"), Some(aenderung))
+            .await
+    }
+
+    /// `DELETE /v1/channels/:id`
+    pub async fn kanal_loeschen(&self, id: Uuid) -> ClientResult<()> {
+        self.ohne_antwort(Method::DELETE, &format!("/v1/channels/{id}"), KEIN_RUMPF)
+            .await
+    }
+
+    /// `POST /v1/channels/:id/emergency-mute`
+    pub async fn kanal_notfall_stumm(
+        &self,
+        id: Uuid,
+        aktivieren: bool,
+    ) -> ClientResult<NotfallStummErgebnis> {
+        self.json(
+            Method::POST,
+            &format!("/v1/channels/{id}/emergency-mute"),
+            Some(&NotfallStummBody { aktivieren }),
+        )
+        .await
+    }
+
+    /// `GET /v1/channels/:id/speakers` (User-IDs der gerade Sprechenden)
+    pub async fn aktive_sprecher(&self, kanal_id: Uuid) -> ClientResult<Vec<Uuid>> {
+        self.json(
+            Method::GET,
+            &format!("/v1/channels/{kanal_id}/speakers"),
+            KEIN_RUMPF,
+        )
+        .await
+    }
+
+    /// `POST /v1/channels/:id/move-clients`
+    pub async fn clients_verschieben(
+        &self,
+        von_kanal_id: Uuid,
+        anfrage: &MoveAllBody,
+    ) -> ClientResult<SammelVerschiebungErgebnis> {
+        self.json(
+            Method::POST,
+            &format!("/v1/channels/{von_kanal_id}/move-clients"),
+            Some(anfrage),
+        )
+        .await
+    }
+
+    // -----------------------------------------------------------------------
+    // Kanal-Vorlagen
+    // -----------------------------------------------------------------------
+
+    /// `GET /v1/channel-templates`
+    pub async fn vorlagen(&self) -> ClientResult<Vec<VorlageInfo>> {
+        self.json(Method::GET, "/v1/channel-templates", KEIN_RUMPF)
+            .await
+    }
+
+    /// `POST /v1/channel-templates`
+    pub async fn vorlage_erstellen(
+        &self,
+        vorlage: &VorlageErstellenBody,
+    ) -> ClientResult<VorlageInfo> {
+        self.json(Method::POST, "/v1/channel-templates", Some(vorlage))
+            .await
+    }
+
+    /// `DELETE /v1/channel-templates/:id`
+    pub async fn vorlage_loeschen(&self, id: Uuid) -> ClientResult<()> {
+        self.ohne_antwort(
+            Method::DELETE,
+            &format!("/v1/channel-templates/{id}"),
+            KEIN_RUMPF,
+        )
+        .await
+    }
+
+    /// `POST /v1/channel-templates/:id/instantiate` (neuer Kanalbaum, Wurzel zuerst)
+    pub async fn vorlage_instanziieren(
+        &self,
+        id: Uuid,
+        anfrage: &InstanziierenBody,
+    ) -> ClientResult<Vec<KanalInfo>> {
+        self.json(
+            Method::POST,
+            &format!("/v1/channel-templates/{id}/instantiate"),
+            Some(anfrage),
+        )
+        .await
+    }
+
+    // -----------------------------------------------------------------------
+    // Clients
+    // -----------------------------------------------------------------------
+
+    /// Alle verbundenen Clients (laedt saemtliche Seiten von `GET /v1/clients`)
+    pub async fn clients(&self) -> ClientResult<Vec<ClientInfo>> {
+        self.clients_seitenweise(ClientQuery::default(), MAX_SEITENGROESSE)
+            .alle()
+            .await
+    }
+
+    /// `GET /v1/clients` (eine Seite)
+    pub async fn client_seite(&self, filter: &ClientQuery) -> ClientResult<ClientSeite> {
+        let pfad = mit_query("/v1/clients", filter)?;
+        self.json(Method::GET, &pfad, KEIN_RUMPF).await
+    }
+
+    /// Client-Liste seitenweise (`page`/`page_size` aus `filter` entfallen)
+    pub fn clients_seitenweise(
+        &self,
+        filter: ClientQuery,
+        seitengroesse: u32,
+    ) -> Seiten<'_, ClientInfo> {
+        Seiten::neu(seitengroesse, move |limit, offset| {
+            let filter = ClientQuery {
+                page: Some(offset / limit + 1),
+                page_size: Some(limit),
+                ..filter.clone()
+            };
+            Box::pin(async move {
+                let seite = self.client_seite(&filter).await?;
+                Ok((seite.clients, Some(seite.seite.total_count)))
+            })
+        })
+    }
+
+    /// `POST /v1/clients/:id/kick`
+    pub async fn client_kicken(&self, id: Uuid, anfrage: &KickBody) -> ClientResult<()> {
+        self.ohne_antwort(
+            Method::POST,
+            &format!("/v1/clients/{id}/kick"),
+            Some(anfrage),
+        )
+        .await
+    }
+
+    /// `POST /v1/clients/:id/ban`
+    pub async fn client_bannen(&self, id: Uuid, anfrage: &BanBody) -> ClientResult<()> {
+        self.ohne_antwort(
+            Method::POST,
+            &format!("/v1/clients/{id}/ban"),
+            Some(anfrage),
+        )
+        .await
+    }
+
+    /// `POST /v1/clients/:id/move`
+    pub async fn client_verschieben(&self, id: Uuid, kanal_id: Uuid) -> ClientResult<()> {
+        self.ohne_antwort(
+            Method::POST,
+            &format!("/v1/clients/{id}/move"),
+            Some(&MoveBody { kanal_id }),
+        )
+        .await
+    }
+
+    /// `POST /v1/clients/:id/poke`
+    pub async fn client_poken(&self, id: Uuid, nachricht: impl Into<String>) -> ClientResult<()> {
+        self.ohne_antwort(
+            Method::POST,
+            &format!("/v1/clients/{id}/poke"),
+            Some(&PokeBody {
+                nachricht: nachricht.into(),
+            }),
+        )
+        .await
+    }
+
+    // -----------------------------------------------------------------------
+    // Berechtigungen
+    // -----------------------------------------------------------------------
+
+    /// `GET /v1/permissions/:ziel` (z.B. `user:<uuid>`, `group:<uuid>`)
+    pub async fn berechtigungen(&self, ziel: &str) -> ClientResult<Vec<BerechtigungsEintrag>> {
+        self.json(
+            Method::GET,
+            &format!("/v1/permissions/{}", segment(ziel)),
+            KEIN_RUMPF,
+        )
+        .await
+    }
+
+    /// `POST /v1/permissions`
+    pub async fn berechtigung_setzen(&self, anfrage: &SetPermissionBody) -> ClientResult<()> {
+        self.ohne_antwort(Method::POST, "/v1/permissions", Some(anfrage))
+            .await
+    }
+
+    /// `DELETE /v1/permissions/:permission`
+    pub async fn berechtigung_entfernen(
+        &self,
+        permission: &str,
+        anfrage: &RemovePermissionBody,
+    ) -> ClientResult<()> {
+        self.ohne_antwort(
+            Method::DELETE,
+            &format!("/v1/permissions/{}", segment(permission)),
+            Some(anfrage),
+        )
+        .await
+    }
+
+    /// `GET /v1/users/:id/effective-permissions` (ohne Kanal: Server-Root)
+    pub async fn effektive_berechtigungen(
+        &self,
+        user_id: Uuid,
+        kanal_id: Option<Uuid>,
+    ) -> ClientResult<Vec<EffektiverBerechtigungsEintrag>> {
+        let pfad = mit_query(
+            &format!("/v1/users/{user_id}/effective-permissions"),
+            &EffektivQuery { channel: kanal_id },
+        )?;
+        self.json(Method::GET, &pfad, KEIN_RUMPF).await
+    }
+
+    // -----------------------------------------------------------------------
+    // Benutzerverwaltung
+    // -----------------------------------------------------------------------
+
+    /// `GET /v1/users` (auch gesperrte Konten)
+    pub async fn benutzer(&self, filter: &BenutzerQuery) -> ClientResult<BenutzerSeite> {
+        let pfad = mit_query("/v1/users", filter)?;
+        self.json(Method::GET, &pfad, KEIN_RUMPF).await
+    }
+
+    /// `PUT /v1/users/:id/password` (beendet die Sessions des Benutzers)
+    pub async fn benutzer_passwort_setzen(
+        &self,
+        user_id: Uuid,
+        neues_passwort: &str,
+    ) -> ClientResult<BenutzerInfo> {
+        let anfrage = PasswortSetzenBody {
+            neues_passwort: neues_passwort.to_string(),
+        };
+        self.json(
+            Method::PUT,
+            &format!("/v1/users/{user_id}/password"),
+            Some(&anfrage),
+        )
+        .await
+    }
+
+    /// `POST /v1/users/:id/disable` (`false` schaltet wieder frei)
+    pub async fn benutzer_sperren(
+        &self,
+        user_id: Uuid,
+        deaktiviert: bool,
+    ) -> ClientResult<BenutzerInfo> {
+        self.json(
+            Method::POST,
+            &format!("/v1/users/{user_id}/disable"),
+            Some(&DeaktivierenBody { deaktiviert }),
+        )
+        .await
+    }
+
+    /// `POST /v1/users/:id/remove` (sperrt das Konto und widerruft alle Zugaenge)
+    pub async fn benutzer_entfernen(&self, user_id: Uuid) -> ClientResult<()> {
+        self.ohne_antwort(
+            Method::POST,
+            &format!("/v1/users/{user_id}/remove"),
+            KEIN_RUMPF,
+        )
+        .await
+    }
+
+    // -----------------------------------------------------------------------
+    // Konten
+    // -----------------------------------------------------------------------
+
+    /// `POST /v1/users/:id/export` (Einmal-Link zum Archiv)
+    pub async fn konto_exportieren(&self, user_id: Uuid) -> ClientResult<KontoExportErgebnis> {
+        self.json(
+            Method::POST,
+            &format!("/v1/users/{user_id}/export"),
+            KEIN_RUMPF,
+        )
+        .await
+    }
+
+    /// `DELETE /v1/users/:id` (endgueltig, Nachrichten werden anonymisiert)
+    pub async fn konto_loeschen(&self, user_id: Uuid) -> ClientResult<KontoLoeschErgebnis> {
+        self.json(Method::DELETE, &format!("/v1/users/{user_id}"), KEIN_RUMPF)
+            .await
+    }
+
+    /// `POST /v1/users/:id/cleanup` (Nachrichten und Dateien ohne Ban entfernen)
+    pub async fn inhalte_bereinigen(
+        &self,
+        user_id: Uuid,
+        anfrage: &BereinigungBody,
+    ) -> ClientResult<BereinigungsErgebnis> {
+        self.json(
+            Method::POST,
+            &format!("/v1/users/{user_id}/cleanup"),
+            Some(anfrage),
+        )
+        .await
+    }
+
+    // -----------------------------------------------------------------------
+    // Dateien
+    // -----------------------------------------------------------------------
+
+    /// `GET /v1/files/:kanal_id`
+    pub async fn dateien(&self, kanal_id: Uuid) -> ClientResult<Vec<DateiEintrag>> {
+        self.json(Method::GET, &format!("/v1/files/{kanal_id}"), KEIN_RUMPF)
+            .await
+    }
+
+    /// `DELETE /v1/files/:datei_id`
+    pub async fn datei_loeschen(&self, datei_id: &str) -> ClientResult<()> {
+        self.ohne_antwort(
+            Method::DELETE,
+            &format!("/v1/files/{}", segment(datei_id)),
+            KEIN_RUMPF,
+        )
+        .await
+    }
+
+    /// `GET /v1/files/access-log` (eine Seite)
+    pub async fn datei_zugriffe(&self, filter: &ZugriffsQuery) -> ClientResult<DateiZugriffSeite> {
+        let pfad = mit_query("/v1/files/access-log", filter)?;
+        self.json(Method::GET, &pfad, KEIN_RUMPF).await
+    }
+
+    /// Datei-Zugriffsprotokoll seitenweise (`limit`/`offset` aus `filter` entfallen)
+    pub fn datei_zugriffe_seitenweise(
+        &self,
+        filter: ZugriffsQuery,
+        seitengroesse: u32,
+    ) -> Seiten<'_, DateiZugriffEintrag> {
+        Seiten::neu(seitengroesse, move |limit, offset| {
+            let filter = ZugriffsQuery {
+                limit: Some(limit),
+                offset: Some(offset),
+                ..filter.clone()
+            };
+            Box::pin(async move {
+                let seite = self.datei_zugriffe(&filter).await?;
+                Ok((seite.eintraege, Some(seite.gesamt)))
+            })
+        })
+    }
+
+    // -----------------------------------------------------------------------
+    // Soundboard
+    // -----------------------------------------------------------------------
+
+    /// `GET /v1/soundboard` (mit Kanal: serverweite und die des Kanals)
+    pub async fn sounds(&self, kanal_id: Option<Uuid>) -> ClientResult<Vec<SoundInfo>> {
+        let pfad = mit_query("/v1/soundboard", &SoundQuery { channel: kanal_id })?;
+        self.json(Method::GET, &pfad, KEIN_RUMPF).await
+    }
+
+    /// `POST /v1/soundboard` (kodiert die hochgeladene Datei auf dem Server)
+    pub async fn sound_registrieren(
+        &self,
+        anfrage: &SoundRegistrierenBody,
+    ) -> ClientResult<SoundInfo> {
+        self.json(Method::POST, "/v1/soundboard", Some(anfrage))
+            .await
+    }
+
+    /// `DELETE /v1/soundboard/:id`
+    pub async fn sound_entfernen(&self, id: Uuid) -> ClientResult<()> {
+        self.ohne_antwort(Method::DELETE, &format!("/v1/soundboard/{id}"), KEIN_RUMPF)
+            .await
+    }
+
+    // -----------------------------------------------------------------------
+    // Audit-Log
+    // -----------------------------------------------------------------------
+
+    /// `GET /v1/logs` (eine Seite)
+    pub async fn logs(&self, filter: &LogQuery) -> ClientResult<Vec<LogEintrag>> {
+        let pfad = mit_query("/v1/logs", filter)?;
+        self.json(Method::GET, &pfad, KEIN_RUMPF).await
+    }
+
+    /// Audit-Log seitenweise, optional nach Aktion gefiltert
+    pub fn logs_seitenweise(
+        &self,
+        aktion: Option<String>,
+        seitengroesse: u32,
+    ) -> Seiten<'_, LogEintrag> {
+        Seiten::neu(seitengroesse, move |limit, offset| {
+            let filter = LogQuery {
+                limit: Some(limit),
+                offset: Some(offset),
+                aktion: aktion.clone(),
+            };
+            Box::pin(async move { Ok((self.logs(&filter).await?, None)) })
+        })
+    }
+
+    // -----------------------------------------------------------------------
+    // Zeitplaner
+    // -----------------------------------------------------------------------
+
+    /// `GET /v1/schedules`
+    pub async fn zeitplaene(&self) -> ClientResult<Vec<ZeitplanInfo>> {
+        self.json(Method::GET, "/v1/schedules", KEIN_RUMPF).await
+    }
+
+    /// `POST /v1/schedules`
+    pub async fn zeitplan_erstellen(
+        &self,
+        zeitplan: &ZeitplanErstellenBody,
+    ) -> ClientResult<ZeitplanInfo> {
+        self.json(Method::POST, "/v1/schedules", Some(zeitplan))
+            .await
+    }
+
+    /// `DELETE /v1/schedules/:id`
+    pub async fn zeitplan_abbrechen(&self, id: Uuid) -> ClientResult<()> {
+        self.ohne_antwort(Method::DELETE, &format!("/v1/schedules/{id}"), KEIN_RUMPF)
+            .await
+    }
+}
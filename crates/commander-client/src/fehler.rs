@@ -0,0 +1,165 @@
+//! Fehlertypen des Commander-Clients
+//!
+//! Fehlerantworten des Servers werden auf die stabilen Codes aus
+//! [`speakeasy_core::FehlerCode`] abgebildet, damit Aufrufer nicht an
+//! HTTP-Statuscodes oder Meldungstexten haengen.
+
+use std::time::Duration;
+
+use serde_json::Value;
+use speakeasy_core::FehlerCode;
+use thiserror::Error;
+
+/// Fehlerantwort der REST-API
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ApiFehler {
+    /// HTTP-Statuscode der Antwort
+    pub status: u16,
+    /// Stabiler Fehlercode aus dem Fehler-Envelope
+    pub code: FehlerCode,
+    /// Meldung des Servers
+    pub meldung: String,
+    /// Wartezeit bei Rate-Limits
+    pub retry_after_secs: Option<u64>,
+}
+
+/// Alle moeglichen Fehler des Commander-Clients
+#[derive(Debug, Error)]
+pub enum ClientFehler {
+    #[error("Ungueltige Server-Adresse: {0}")]
+    Adresse(String),
+
+    #[error("Ungueltige Anfrage: {0}")]
+    Anfrage(String),
+
+    #[error("TLS-Fehler: {0}")]
+    Tls(String),
+
+    #[error("Verbindungsfehler: {0}")]
+    Verbindung(String),
+
+    #[error("Keine Antwort innerhalb von {0:?}")]
+    Zeitlimit(Duration),
+
+    #[error("{} ({}, HTTP {})", .0.meldung, .0.code, .0.status)]
+    Api(ApiFehler),
+
+    #[error("Ungueltige Antwort: {0}")]
+    Antwort(String),
+}
+
+pub type ClientResult<T> = Result<T, ClientFehler>;
+
+impl ClientFehler {
+    /// Stabiler Fehlercode (auch fuer Fehler, die lokal entstehen)
+    pub fn fehler_code(&self) -> FehlerCode {
+        match self {
+            Self::Adresse(_) | Self::Anfrage(_) => FehlerCode::UngueltigeAnfrage,
+            Self::Tls(_) | Self::Verbindung(_) => FehlerCode::Verbindung,
+            Self::Zeitlimit(_) => FehlerCode::Zeitlimit,
+            Self::Api(fehler) => fehler.code,
+            Self::Antwort(_) => FehlerCode::Intern,
+        }
+    }
+
+    /// Ob ein erneuter Versuch sinnvoll sein kann
+    pub fn ist_wiederholbar(&self) -> bool {
+        self.fehler_code().ist_wiederholbar()
+    }
+
+    /// Baut den Fehler aus einer Antwort mit Status >= 400
+    ///
+    /// Versteht beide Envelope-Formen des Servers:
+    /// - `{"error": "<meldung>", "code": "channel.full", "nummer": 3002}`
+    /// - `{"error": {"code": 401, "message": "...", "retry_after_secs": 3}}`
+    ///   (Authentifizierung und Rate-Limit der Middleware)
+    pub(crate) fn aus_antwort(status: u16, rumpf: &[u8]) -> Self {
+        let json: Value = serde_json::from_slice(rumpf).unwrap_or(Value::Null);
+        let (meldung, code, retry_after_secs) = match &json["error"] {
+            Value::String(meldung) => (
+                meldung.clone(),
+                json["code"].as_str().and_then(FehlerCode::aus_name),
+                json["retry_after_secs"].as_u64(),
+            ),
+            Value::Object(fehler) => (
+                fehler
+                    .get("message")
+                    .and_then(Value::as_str)
+                    .unwrap_or_default()
+                    .to_string(),
+                None,
+                fehler.get("retry_after_secs").and_then(Value::as_u64),
+            ),
+            _ => (
+                String::from_utf8_lossy(rumpf).trim().to_string(),
+                None,
+                None,
+            ),
+        };
+        Self::Api(ApiFehler {
+            status,
+            code: code.unwrap_or_else(|| code_aus_status(status)),
+            meldung,
+            retry_after_secs,
+        })
+    }
+}
+
+/// Ersatz-Code, wenn die Antwort keinen stabilen Code traegt
+fn code_aus_status(status: u16) -> FehlerCode {
+    match status {
+        400 | 422 => FehlerCode::UngueltigeAnfrage,
+        401 => FehlerCode::TokenUngueltig,
+        403 => FehlerCode::ZugriffVerweigert,
+        404 => FehlerCode::NichtGefunden,
+        409 => FehlerCode::Konflikt,
+        429 => FehlerCode::RateLimit,
+        503 => FehlerCode::Verbindung,
+        504 => FehlerCode::Zeitlimit,
+        _ => FehlerCode::Intern,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn api(fehler: ClientFehler) -> ApiFehler {
+        match fehler {
+            ClientFehler::Api(api) => api,
+            anderer => panic!("kein API-Fehler: {anderer:?}"),
+        }
+    }
+
+    #[test]
+    fn taxonomie_envelope_liefert_stabilen_code() {
+        let fehler = api(ClientFehler::aus_antwort(
+            409,
+            br#"{"error":"Kanal ist voll","code":"channel.full","nummer":3002}"#,
+        ));
+        assert_eq!(fehler.code, FehlerCode::KanalVoll);
+        assert_eq!(fehler.meldung, "Kanal ist voll");
+        assert_eq!(fehler.status, 409);
+    }
+
+    #[test]
+    fn middleware_envelope_faellt_auf_status_zurueck() {
+        let fehler = api(ClientFehler::aus_antwort(
+            429,
+            br#"{"error":{"code":429,"message":"Rate-Limit ueberschritten","retry_after_secs":7}}"#,
+        ));
+        assert_eq!(fehler.code, FehlerCode::RateLimit);
+        assert_eq!(fehler.retry_after_secs, Some(7));
+
+        let fehler = api(ClientFehler::aus_antwort(401, br#"{"error":{"code":401}}"#));
+        assert_eq!(fehler.code, FehlerCode::TokenUngueltig);
+    }
+
+    #[test]
+    fn rumpf_ohne_json_wird_zur_meldung() {
+        let fehler = ClientFehler::aus_antwort(502, b"Bad Gateway\n");
+        assert_eq!(fehler.fehler_code(), FehlerCode::Intern);
+        assert_eq!(api(fehler).meldung, "Bad Gateway");
+        assert!(ClientFehler::Zeitlimit(Duration::from_secs(1)).ist_wiederholbar());
+    }
+}
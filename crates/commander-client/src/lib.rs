@@ -0,0 +1,20 @@
+//! speakeasy-commander-client – typisierter Client fuer die Commander-REST-API
+//!
+//! - [`CommanderClient`]: async Methoden fuer alle `/v1`-Endpunkte
+//! - Authentifizierung per Bearer-Token und/oder Client-Zertifikat ([`ClientTls`])
+//! - [`Seiten`]: laedt paginierte Endpunkte (Logs, Datei-Zugriffe) seitenweise
+//! - [`ClientFehler`]: Fehlerantworten abgebildet auf [`speakeasy_core::FehlerCode`]
+//!
+//! Anfrage- und Antworttypen kommen aus [`typen`] und sind dieselben, die die
+//! Handler im Commander verwenden.
+
+pub mod client;
+pub mod endpunkte;
+pub mod fehler;
+pub mod seiten;
+
+pub use client::{ClientTls, CommanderClient, STANDARD_ZEITLIMIT};
+pub use fehler::{ApiFehler, ClientFehler, ClientResult};
+pub use seiten::{Seiten, MAX_SEITENGROESSE};
+pub use speakeasy_commander::rest::typen;
+pub use speakeasy_core::FehlerCode;
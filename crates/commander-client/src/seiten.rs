@@ -0,0 +1,121 @@
+//! Seitenweises Laden paginierter Endpunkte
+//!
+//! [`Seiten`] fragt erst beim Aufruf von [`Seiten::naechste`] die naechste
+//! Seite an. Das Ende ist erreicht, sobald eine Seite kuerzer als die
+//! Seitengroesse ist oder die vom Server gemeldete Gesamtzahl erreicht wurde.
+
+use speakeasy_commander::rest::BoxFuture;
+
+use crate::fehler::ClientResult;
+
+/// Groesste Seite, die der Server ausliefert (`limit` wird serverseitig gekappt)
+pub const MAX_SEITENGROESSE: u32 = 1000;
+
+/// Eine geladene Seite und – falls bekannt – die Gesamtzahl der Eintraege
+pub(crate) type Seite<T> = (Vec<T>, Option<u64>);
+
+type Laden<'a, T> = Box<dyn FnMut(u32, u32) -> BoxFuture<'a, ClientResult<Seite<T>>> + Send + 'a>;
+
+/// Lazy Iterator ueber die Seiten eines paginierten Endpunkts
+pub struct Seiten<'a, T> {
+    laden: Laden<'a, T>,
+    seitengroesse: u32,
+    offset: u32,
+    fertig: bool,
+}
+
+impl<'a, T> Seiten<'a, T> {
+    /// `laden(limit, offset)` holt eine Seite vom Server
+    pub(crate) fn neu(
+        seitengroesse: u32,
+        laden: impl FnMut(u32, u32) -> BoxFuture<'a, ClientResult<Seite<T>>> + Send + 'a,
+    ) -> Self {
+        Self {
+            laden: Box::new(laden),
+            seitengroesse: seitengroesse.clamp(1, MAX_SEITENGROESSE),
+            offset: 0,
+            fertig: false,
+        }
+    }
+
+    /// Laedt die naechste Seite (`None` wenn alle Eintraege geliefert wurden)
+    pub async fn naechste(&mut self) -> ClientResult<Option<Vec<T>>> {
+        if self.fertig {
+            return Ok(None);
+        }
+        let (eintraege, gesamt) = (self.laden)(self.seitengroesse, self.offset).await?;
+        let anzahl = eintraege.len() as u32;
+        self.offset = self.offset.saturating_add(anzahl);
+        self.fertig = anzahl < self.seitengroesse
+            || gesamt.is_some_and(|gesamt| u64::from(self.offset) >= gesamt);
+        if eintraege.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(eintraege))
+    }
+
+    /// Laedt alle verbleibenden Seiten
+    pub async fn alle(mut self) -> ClientResult<Vec<T>> {
+        let mut alle = Vec::new();
+        while let Some(seite) = self.naechste().await? {
+            alle.extend(seite);
+        }
+        Ok(alle)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{
+        atomic::{AtomicU32, Ordering},
+        Arc,
+    };
+
+    /// Quelle mit `anzahl` Eintraegen, die die Anzahl der Abrufe mitzaehlt
+    fn quelle<'a>(
+        anzahl: u32,
+        gesamt_melden: bool,
+        abrufe: Arc<AtomicU32>,
+        seitengroesse: u32,
+    ) -> Seiten<'a, u32> {
+        Seiten::neu(seitengroesse, move |limit, offset| {
+            abrufe.fetch_add(1, Ordering::SeqCst);
+            let ende = (offset + limit).min(anzahl);
+            let gesamt = gesamt_melden.then_some(u64::from(anzahl));
+            Box::pin(async move { Ok(((offset..ende).collect(), gesamt)) })
+        })
+    }
+
+    #[tokio::test]
+    async fn laedt_erst_bei_bedarf() {
+        let abrufe = Arc::new(AtomicU32::new(0));
+        let mut seiten = quelle(25, false, Arc::clone(&abrufe), 10);
+        assert_eq!(abrufe.load(Ordering::SeqCst), 0);
+
+        assert_eq!(seiten.naechste().await.unwrap().unwrap().len(), 10);
+        assert_eq!(abrufe.load(Ordering::SeqCst), 1);
+        assert_eq!(seiten.naechste().await.unwrap().unwrap()[0], 10);
+        assert_eq!(seiten.naechste().await.unwrap().unwrap().len(), 5);
+        assert!(seiten.naechste().await.unwrap().is_none());
+        assert_eq!(abrufe.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn gesamtzahl_spart_die_leere_abschlussseite() {
+        let abrufe = Arc::new(AtomicU32::new(0));
+        let alle = quelle(20, true, Arc::clone(&abrufe), 10)
+            .alle()
+            .await
+            .unwrap();
+        assert_eq!(alle, (0..20).collect::<Vec<_>>());
+        assert_eq!(abrufe.load(Ordering::SeqCst), 2);
+
+        let abrufe = Arc::new(AtomicU32::new(0));
+        quelle(20, false, Arc::clone(&abrufe), 10)
+            .alle()
+            .await
+            .unwrap();
+        assert_eq!(abrufe.load(Ordering::SeqCst), 3);
+    }
+}
@@ -0,0 +1,340 @@
+//! Client gegen einen Commander-REST-Server im selben Prozess
+
+use std::sync::Arc;
+
+use speakeasy_auth::{ApiTokenStore, AuthService, BanService, PermissionService, SessionStore};
+use speakeasy_commander::auth::{AuthArt, CommanderSession};
+use speakeasy_commander::rest::server::RestServerKonfig;
+use speakeasy_commander::rest::{CommanderState, ExecutorFn, RestServer, TokenValidatorFn};
+use speakeasy_commander::{CommandExecutor, CommanderError, RateLimitKonfig, RateLimiter};
+use speakeasy_commander_client::typen::{
+    BenutzerQuery, KanalBearbeitenBody, KanalErstellenBody, KanalQuery, LogQuery,
+};
+use speakeasy_commander_client::{ClientFehler, CommanderClient, FehlerCode};
+use speakeasy_db::{einstellungen::ServerEinstellungen, models::KanalbaumGrenzen, SqliteDb};
+use uuid::Uuid;
+
+const TOKEN: &str = "geheim";
+
+/// Startet den REST-Server auf einem freien Port und liefert dessen URL
+async fn server_starten() -> String {
+    let db = Arc::new(SqliteDb::in_memory().await.unwrap());
+    let auth_service = Arc::new(AuthService::neu(
+        Arc::clone(&db),
+        SessionStore::neu(),
+        ApiTokenStore::neu(),
+    ));
+    let benutzer = auth_service
+        .registrieren("operator", "sicheres-passwort-123")
+        .await
+        .unwrap();
+    auth_service
+        .registrieren("gast", "gast-passwort-123")
+        .await
+        .unwrap();
+    let executor = CommandExecutor::neu(
+        Arc::clone(&db),
+        Arc::clone(&db),
+        Arc::clone(&db),
+        Arc::clone(&db),
+        Arc::clone(&db),
+        Arc::clone(&db),
+        Arc::clone(&db),
+        Arc::clone(&db),
+        Arc::clone(&db),
+        auth_service,
+        PermissionService::neu(Arc::clone(&db)),
+        BanService::neu(Arc::clone(&db)),
+        ServerEinstellungen {
+            name: "Integration".into(),
+            willkommensnachricht: None,
+            max_clients: 32,
+            host_nachricht: None,
+            chat_bearbeitungsfrist_sek: 0,
+        },
+        "0.0.0".into(),
+        KanalbaumGrenzen::default(),
+    );
+    let executor_fn: ExecutorFn = Arc::new(move |cmd, session| {
+        let exec = Arc::clone(&executor);
+        Box::pin(async move { exec.ausfuehren(cmd, &session).await })
+    });
+    let token_validator: TokenValidatorFn = Arc::new(move |token| {
+        if token != TOKEN {
+            return Err(CommanderError::Authentifizierung("unbekannt".into()));
+        }
+        Ok(CommanderSession {
+            benutzer: benutzer.clone(),
+            scopes: vec![],
+            auth_art: AuthArt::Session,
+        })
+    });
+
+    let (tx, rx) = tokio::sync::oneshot::channel();
+    let server = RestServer::neu(RestServerKonfig {
+        bind_addr: "127.0.0.1:0".parse().unwrap(),
+        cors_origins: vec![],
+        tls: None,
+    })
+    .bei_bereitschaft(move |addr| {
+        let _ = tx.send(addr);
+    });
+    let state = CommanderState::neu(executor_fn, token_validator);
+    tokio::spawn(async move {
+        // Der Server laeuft bis zum Ende des Tests
+        let (_shutdown_tx, shutdown_rx) = tokio::sync::watch::channel(false);
+        server
+            .starten(
+                state,
+                RateLimiter::neu(RateLimitKonfig::default()),
+                shutdown_rx,
+            )
+            .await
+            .unwrap();
+    });
+    format!("http://{}", rx.await.unwrap())
+}
+
+async fn client() -> CommanderClient {
+    CommanderClient::neu(&server_starten().await)
+        .unwrap()
+        .mit_token(TOKEN)
+}
+
+fn kanal(name: &str) -> KanalErstellenBody {
+    KanalErstellenBody {
+        name: name.into(),
+        ..Default::default()
+    }
+}
+
+#[tokio::test]
+async fn server_info_wird_gelesen() {
+    let info = client().await.server_info().await.unwrap();
+    assert_eq!(info.name, "Integration");
+    assert!(!info.letzter_shutdown_unsauber);
+}
+
+#[tokio::test]
+async fn server_historie_ist_anfangs_leer() {
+    let starts = client().await.server_historie(Some(5)).await.unwrap();
+    assert!(starts.is_empty());
+}
+
+#[tokio::test]
+async fn motd_wird_versioniert() {
+    let client = client().await;
+    let erste = client.motd_setzen("Hallo <b>Welt</b>").await.unwrap();
+    assert_eq!(erste.version, 1);
+    assert_eq!(erste.markdown, "Hallo &lt;b>Welt&lt;/b>");
+    assert_eq!(
+        client.motd_setzen("Hallo <b>Welt</b>").await.unwrap(),
+        erste
+    );
+    assert_eq!(client.motd_setzen("").await.unwrap().version, 2);
+}
+
+#[tokio::test]
+async fn kanal_anlegen_bearbeiten_loeschen() {
+    let client = client().await;
+    let vorher = client.kanaele().await.unwrap().len();
+
+    let neu = client.kanal_erstellen(&kanal("Lobby")).await.unwrap();
+    assert_eq!(neu.name, "Lobby");
+    assert!(client
+        .kanaele()
+        .await
+        .unwrap()
+        .iter()
+        .any(|k| k.id == neu.id));
+
+    let geaendert = client
+        .kanal_bearbeiten(
+            neu.id,
+            &KanalBearbeitenBody {
+                thema: Some("Willkommen".into()),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+    assert_eq!(geaendert.thema.as_deref(), Some("Willkommen"));
+
+    client.kanal_loeschen(neu.id).await.unwrap();
+    assert_eq!(client.kanaele().await.unwrap().len(), vorher);
+}
+
+#[tokio::test]
+async fn fehler_werden_auf_stabile_codes_abgebildet() {
+    let url = server_starten().await;
+
+    let anonym = CommanderClient::neu(&url).unwrap();
+    let fehler = anonym.kanaele().await.unwrap_err();
+    assert_eq!(fehler.fehler_code(), FehlerCode::TokenUngueltig);
+    assert!(matches!(fehler, ClientFehler::Api(ref api) if api.status == 401));
+
+    let client = CommanderClient::neu(&url).unwrap().mit_token(TOKEN);
+    let fehler = client.kanal_loeschen(Uuid::new_v4()).await.unwrap_err();
+    let ClientFehler::Api(api) = fehler else {
+        panic!("API-Fehler erwartet");
+    };
+    assert_eq!(api.status, 404);
+    assert_eq!(api.code.http_status(), 404);
+}
+
+#[tokio::test]
+async fn logs_werden_seitenweise_geladen() {
+    let client = client().await;
+    for i in 0..5 {
+        client
+            .kanal_erstellen(&kanal(&format!("Raum {i}")))
+            .await
+            .unwrap();
+    }
+    let alle = client
+        .logs(&LogQuery {
+            limit: Some(1000),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    assert!(alle.len() >= 5);
+
+    let mut seiten = client.logs_seitenweise(None, 2);
+    let mut geladen = Vec::new();
+    while let Some(seite) = seiten.naechste().await.unwrap() {
+        assert!(seite.len() <= 2);
+        geladen.extend(seite);
+    }
+    let ids = |eintraege: &[speakeasy_commander_client::typen::LogEintrag]| {
+        eintraege.iter().map(|e| e.id).collect::<Vec<_>>()
+    };
+    assert_eq!(ids(&geladen), ids(&alle));
+}
+
+#[tokio::test]
+async fn kanaele_gefiltert_und_seitenweise() {
+    let client = client().await;
+    for name in ["Raid 1", "Musik", "Raid 2", "Raid 3"] {
+        client.kanal_erstellen(&kanal(name)).await.unwrap();
+    }
+
+    let filter = KanalQuery {
+        name: Some("raid".into()),
+        ..Default::default()
+    };
+    let seite = client
+        .kanal_seite(&KanalQuery {
+            page: Some(2),
+            page_size: Some(2),
+            ..filter.clone()
+        })
+        .await
+        .unwrap();
+    assert_eq!(seite.kanaele.len(), 1);
+    assert_eq!(seite.seite.total_count, 3);
+    assert!(!seite.seite.has_next_page);
+
+    let leer = client
+        .kanal_seite(&KanalQuery {
+            page: Some(9),
+            ..filter.clone()
+        })
+        .await
+        .unwrap();
+    assert!(leer.kanaele.is_empty());
+    assert_eq!(leer.seite.total_count, 3);
+
+    let alle = client.kanaele_seitenweise(filter, 2).alle().await.unwrap();
+    let namen: Vec<_> = alle.iter().map(|k| k.name.as_str()).collect();
+    assert_eq!(namen.len(), 3);
+    assert!(namen.iter().all(|n| n.starts_with("Raid")));
+    assert!(client.kanaele().await.unwrap().len() >= 4);
+}
+
+/// Laedt den bei Serverstart registrierten Benutzer "gast"
+async fn gast_laden(client: &CommanderClient) -> speakeasy_commander_client::typen::BenutzerInfo {
+    let filter = BenutzerQuery {
+        filter: Some("gast".into()),
+        ..Default::default()
+    };
+    let mut seite = client.benutzer(&filter).await.unwrap();
+    assert_eq!(seite.gesamt, 1);
+    seite.benutzer.remove(0)
+}
+
+#[tokio::test]
+async fn benutzer_werden_gefiltert_gelistet() {
+    let client = client().await;
+    let seite = client
+        .benutzer(&BenutzerQuery {
+            limit: Some(1),
+            offset: Some(1),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    assert_eq!(seite.gesamt, 2);
+    assert_eq!(seite.benutzer.len(), 1);
+    assert_eq!(seite.benutzer[0].username, "operator");
+
+    let gast = gast_laden(&client).await;
+    assert!(gast.aktiv);
+    assert!(!gast.administrator);
+}
+
+#[tokio::test]
+async fn benutzer_passwort_wird_gesetzt() {
+    let client = client().await;
+    let gast = gast_laden(&client).await;
+
+    let fehler = client
+        .benutzer_passwort_setzen(gast.id, "kurz")
+        .await
+        .unwrap_err();
+    assert!(matches!(fehler, ClientFehler::Api(ref api) if api.status == 400));
+
+    let info = client
+        .benutzer_passwort_setzen(gast.id, "neues-passwort-123")
+        .await
+        .unwrap();
+    assert_eq!(info.id, gast.id);
+    assert!(!info.passwort_geaendert);
+}
+
+#[tokio::test]
+async fn benutzer_wird_gesperrt_und_freigeschaltet() {
+    let client = client().await;
+    let gast = gast_laden(&client).await;
+
+    assert!(!client.benutzer_sperren(gast.id, true).await.unwrap().aktiv);
+    assert!(client.benutzer_sperren(gast.id, false).await.unwrap().aktiv);
+
+    let fehler = client
+        .benutzer_sperren(Uuid::new_v4(), true)
+        .await
+        .unwrap_err();
+    assert!(matches!(fehler, ClientFehler::Api(ref api) if api.status == 404));
+}
+
+#[tokio::test]
+async fn benutzer_wird_entfernt() {
+    let client = client().await;
+    let gast = gast_laden(&client).await;
+
+    client.benutzer_entfernen(gast.id).await.unwrap();
+    let entfernt = gast_laden(&client).await;
+    assert_eq!(entfernt.id, gast.id);
+    assert!(!entfernt.aktiv);
+}
+
+#[tokio::test]
+async fn nicht_erreichbarer_server_ist_verbindungsfehler() {
+    let fehler = CommanderClient::neu("http://127.0.0.1:1")
+        .unwrap()
+        .server_info()
+        .await
+        .unwrap_err();
+    assert!(matches!(fehler, ClientFehler::Verbindung(_)));
+    assert!(fehler.ist_wiederholbar());
+}
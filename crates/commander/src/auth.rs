@@ -1,14 +1,18 @@
 //! Commander-Authentifizierung
 //!
 //! Validiert Session-Tokens und API-Tokens fuer alle drei Interfaces
-//! (REST, TCP, gRPC).
+//! (REST, TCP, gRPC). Bei mTLS kann zusaetzlich ein verifiziertes
+//! Client-Zertifikat ueber eine [`ZertifikatsZuordnung`] einen Benutzer
+//! mit festen Scopes ausweisen.
 
 use std::sync::Arc;
 
+use serde::{Deserialize, Serialize};
 use speakeasy_auth::{session::Session, AuthService};
 use speakeasy_db::{models::BenutzerRecord, repository::UserRepository};
 
 use crate::error::{CommanderError, CommanderResult};
+use crate::tls::ClientIdentitaet;
 
 /// Identitaet einer authentifizierten Commander-Session
 #[derive(Debug, Clone)]
@@ -28,6 +32,8 @@ pub enum AuthArt {
     Session,
     /// API-Token (langlebig, mit Scopes)
     ApiToken,
+    /// Client-Zertifikat (mTLS, Scopes aus der Zuordnung)
+    Zertifikat,
 }
 
 impl CommanderSession {
@@ -36,12 +42,25 @@ impl CommanderSession {
         match self.auth_art {
             // Session-Auth hat alle Rechte (wie Admin-Login)
             AuthArt::Session => true,
-            // API-Token: Scope muss explizit vorhanden sein
-            AuthArt::ApiToken => self.scopes.iter().any(|s| s == scope || s == "admin:*"),
+            // API-Token und Zertifikat: Scope muss explizit vorhanden sein
+            AuthArt::ApiToken | AuthArt::Zertifikat => {
+                self.scopes.iter().any(|s| s == scope || s == "admin:*")
+            }
         }
     }
 }
 
+/// Ordnet Client-Zertifikate einem Commander-Benutzer zu
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ZertifikatsZuordnung {
+    /// Common Name oder alternativer Name (DNS, E-Mail, URI) des Zertifikats
+    pub name: String,
+    /// Benutzername, in dessen Namen gehandelt wird
+    pub benutzer: String,
+    /// Gewaehrte Scopes (wie bei API-Tokens)
+    pub scopes: Vec<String>,
+}
+
 /// Commander-Auth-Service
 ///
 /// Wrapper um den AuthService der speakeasy-auth-Crate fuer
@@ -95,6 +114,40 @@ impl<U: UserRepository> CommanderAuth<U> {
         }
     }
 
+    /// Weist einen Benutzer anhand eines verifizierten Client-Zertifikats aus
+    ///
+    /// Die erste passende Zuordnung gilt. Ohne passende Zuordnung oder bei
+    /// gesperrtem Benutzer schlaegt die Authentifizierung fehl.
+    pub async fn zertifikat_validieren(
+        &self,
+        identitaet: &ClientIdentitaet,
+        zuordnungen: &[ZertifikatsZuordnung],
+    ) -> CommanderResult<CommanderSession> {
+        let zuordnung = zuordnungen
+            .iter()
+            .find(|z| identitaet.passt(&z.name))
+            .ok_or_else(|| {
+                CommanderError::Authentifizierung(format!(
+                    "Client-Zertifikat {identitaet} ist keinem Benutzer zugeordnet"
+                ))
+            })?;
+        let benutzer = self
+            .auth_service
+            .aktiven_benutzer_laden(&zuordnung.benutzer)
+            .await
+            .map_err(|_| {
+                CommanderError::Authentifizierung(format!(
+                    "Benutzer '{}' fuer Client-Zertifikat nicht verfuegbar",
+                    zuordnung.benutzer
+                ))
+            })?;
+        Ok(CommanderSession {
+            benutzer,
+            scopes: zuordnung.scopes.clone(),
+            auth_art: AuthArt::Zertifikat,
+        })
+    }
+
     /// Login fuer TCP-Interface: Benutzername + Passwort -> Session-Token
     pub async fn anmelden(
         &self,
@@ -255,6 +308,42 @@ mod tests {
         assert!(!session.hat_scope("admin:write"));
     }
 
+    #[tokio::test]
+    async fn zertifikat_nur_mit_zuordnung() {
+        let auth = test_auth();
+        auth.auth_service
+            .registrieren("operator", "sicheres-passwort-123")
+            .await
+            .unwrap();
+        let zuordnungen = vec![ZertifikatsZuordnung {
+            name: "ops.example.org".into(),
+            benutzer: "operator".into(),
+            scopes: vec!["cmd:serverinfo".into()],
+        }];
+
+        let identitaet = ClientIdentitaet {
+            subjekt: Some("operator-1".into()),
+            alt_namen: vec!["ops.example.org".into()],
+        };
+        let session = auth
+            .zertifikat_validieren(&identitaet, &zuordnungen)
+            .await
+            .unwrap();
+        assert_eq!(session.benutzer.username, "operator");
+        assert_eq!(session.auth_art, AuthArt::Zertifikat);
+        assert!(session.hat_scope("cmd:serverinfo"));
+        assert!(!session.hat_scope("cmd:serveredit"));
+
+        let fremd = ClientIdentitaet {
+            subjekt: Some("fremd".into()),
+            alt_namen: vec![],
+        };
+        assert!(matches!(
+            auth.zertifikat_validieren(&fremd, &zuordnungen).await,
+            Err(CommanderError::Authentifizierung(_))
+        ));
+    }
+
     #[test]
     fn api_token_wildcard_scope() {
         let session = CommanderSession {
@@ -3,70 +3,262 @@
 //! REST, TCP und gRPC nutzen alle denselben CommandExecutor.
 //! Er enthaelt die gesamte Geschaeftslogik fuer alle Befehle.
 
-use std::sync::Arc;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
 
 use chrono::Utc;
+use tokio::sync::broadcast;
 use uuid::Uuid;
 
 use speakeasy_auth::{AuthService, BanService, PermissionService};
 use speakeasy_db::{
+    audit_puffer::AuditSink,
+    einstellungen::{EinstellungsAenderung, EinstellungsCache, ServerEinstellungen},
     models::{
-        AuditLogFilter, BerechtigungsWert, BerechtigungsZiel, KanalUpdate, NeuerBan, NeuerKanal,
-        TriState,
+        AuditLogFilter, BenutzerRecord, BenutzerUpdate, BerechtigungsWert, BerechtigungsZiel,
+        DateiZugriffFilter, GeplanteAktion, GeplanteAktionRecord, KanalFilter, KanalRecord,
+        KanalUpdate, KanalVorlageRecord, KanalbaumGrenzen, NeueGeplanteAktion, NeueKanalVorlage,
+        NeuerAuditEintrag, NeuerBan, NeuerKanal, NeuerSound, ServerStartRecord, SoundRecord,
+        TriState, VorlagenKnoten, MAX_BEARBEITUNGSFRIST_SEK, MAX_LANGSAMMODUS_SEK,
     },
+    permissions::{BerechtigungsSpur, SpurEintrag},
     repository::{
-        AuditLogRepository, BanRepository, ChannelRepository, PermissionRepository, UserRepository,
+        AuditLogRepository, BanRepository, ChannelRepository, ChannelTemplateRepository,
+        ChatMessageRepository, FileRepository, PermissionRepository, ServerStartRepository,
+        SettingsRepository, SoundboardRepository, UserRepository, ZeitplanRepository,
     },
+    zeitplan::Zeitplan,
+    DbError,
 };
+use speakeasy_observability::alarm::{AlarmMeldung, AlarmStatus};
 
 use crate::{
-    auth::CommanderSession,
+    auth::{AuthArt, CommanderSession},
     commands::types::{
-        BerechtigungsEintrag, BerechtigungsWertInput, Command, KanalInfo, LogEintrag, Response,
-        ServerInfoResponse,
+        BenutzerInfo, BenutzerSeite, BerechtigungsEintrag, BerechtigungsRegel,
+        BerechtigungsWertInput, BereinigungsAuftrag, BereinigungsErgebnis, ClientInfo, ClientSeite,
+        Command, CommanderEreignis, DateiEintrag, DateiZugriffEintrag, DateiZugriffSeite,
+        EffektiverBerechtigungsEintrag, KanalInfo, KanalSeite, KodierterSound, KontoAuftrag,
+        KontoExportErgebnis, KontoLoeschErgebnis, LogEintrag, NachrichtInfo, NotfallStummAuftrag,
+        NotfallStummErgebnis, Response, SammelVerschiebung, SammelVerschiebungErgebnis,
+        SeitenAnfrage, ServerInfoResponse, ServerStartInfo, SoundInfo, VoiceDiagnoseInfo,
+        VorlageInfo, ZeitplanInfo,
     },
     error::{CommanderError, CommanderResult},
+    rest::BoxFuture,
+    sicherung::{self, FortschrittFn, SicherungsAuftrag, SicherungsBericht},
 };
 
+/// Kapazitaet des Ereignis-Kanals (langsame Abonnenten verlieren alte Ereignisse)
+const EREIGNIS_KAPAZITAET: usize = 64;
+
+/// Hoechstdauer eines Soundboard-Sounds
+pub const SOUNDBOARD_MAX_DAUER: Duration = Duration::from_secs(10);
+
+/// Hoechstlaenge eines Sound-Namens in Zeichen
+const SOUND_NAME_MAX: usize = 64;
+
+/// Mindestlaenge eines vom Administrator gesetzten Passworts in Zeichen
+pub const MIN_PASSWORT_LAENGE: usize = 8;
+
+/// Berechtigung, die ein Konto als Administrator ausweist (ausdruecklich
+/// gewaehrt auf Server-Ebene)
+pub const ADMIN_BERECHTIGUNG: &str = "b_server_modify";
+
+/// Type-erased Sammel-Move im Signaling-Dienst
+///
+/// Die Presence der verbundenen Clients lebt im Signaling-Dienst; der Server
+/// setzt die Funktion nach dem Start per
+/// [`CommandExecutor::client_verschieber_setzen`].
+pub type ClientVerschieberFn = Arc<
+    dyn Fn(SammelVerschiebung) -> BoxFuture<'static, CommanderResult<SammelVerschiebungErgebnis>>
+        + Send
+        + Sync,
+>;
+
+/// Type-erased Notfall-Stummschaltung im Signaling-Dienst
+///
+/// Routing und Presence leben im Signaling-/Voice-Dienst; der Server setzt
+/// die Funktion nach dem Start per [`CommandExecutor::notfall_stumm_setzen`].
+pub type NotfallStummFn = Arc<
+    dyn Fn(NotfallStummAuftrag) -> BoxFuture<'static, CommanderResult<NotfallStummErgebnis>>
+        + Send
+        + Sync,
+>;
+
+/// Type-erased Datenexport eines Kontos
+///
+/// Archiv und Dateispeicher kennt nur der Server; er setzt die Funktion nach
+/// dem Start per [`CommandExecutor::konto_export_setzen`].
+pub type KontoExportFn = Arc<
+    dyn Fn(KontoAuftrag) -> BoxFuture<'static, CommanderResult<KontoExportErgebnis>> + Send + Sync,
+>;
+
+/// Type-erased Kontoloeschung
+///
+/// Neben Datenbank und Dateispeicher muss der Server verbundene Clients des
+/// Benutzers trennen; er setzt die Funktion per
+/// [`CommandExecutor::konto_loeschen_setzen`].
+pub type KontoLoeschenFn = Arc<
+    dyn Fn(KontoAuftrag) -> BoxFuture<'static, CommanderResult<KontoLoeschErgebnis>> + Send + Sync,
+>;
+
+/// Type-erased Inhaltsbereinigung im Signaling-Dienst
+///
+/// Verbundene Clients muessen die Loeschungen sehen; der Server setzt die
+/// Funktion per [`CommandExecutor::inhalte_bereinigen_setzen`]. Den
+/// Audit-Eintrag mit den Zahlen pro Kanal schreibt der Signaling-Dienst.
+pub type InhalteBereinigenFn = Arc<
+    dyn Fn(BereinigungsAuftrag) -> BoxFuture<'static, CommanderResult<BereinigungsErgebnis>>
+        + Send
+        + Sync,
+>;
+
+/// Type-erased Abfrage der aktiven Sprecher eines Kanals
+///
+/// Der Sprechzustand lebt im Voice-/Signaling-Dienst; der Server setzt die
+/// Funktion nach dem Start per [`CommandExecutor::sprecher_abfrage_setzen`].
+pub type SprecherAbfrageFn = Arc<dyn Fn(Uuid) -> Vec<Uuid> + Send + Sync>;
+
+/// Type-erased Voice-Diagnose eines Clients
+///
+/// Liefert `None`, wenn der Client keine Voice-Verbindung hat; der Server
+/// setzt die Funktion per [`CommandExecutor::voice_diagnose_setzen`].
+pub type VoiceDiagnoseFn = Arc<dyn Fn(Uuid) -> Option<VoiceDiagnoseInfo> + Send + Sync>;
+
+/// Type-erased sofortige Auswertung der Alarmregeln
+///
+/// Regeln, Messwerte und Zustellung kennt nur der Server; er setzt die
+/// Funktion nach dem Start per [`CommandExecutor::alarm_pruefung_setzen`].
+pub type AlarmPruefungFn = Arc<dyn Fn() -> BoxFuture<'static, Vec<AlarmStatus>> + Send + Sync>;
+
+/// Type-erased Sicherung (siehe [`sicherung::erstellen`])
+///
+/// Datenbank-Datei, Datei-Speicher und Konfiguration kennt nur der Server;
+/// er setzt die Funktion nach dem Start per [`CommandExecutor::backup_setzen`].
+pub type BackupFn = Arc<
+    dyn Fn(SicherungsAuftrag) -> BoxFuture<'static, CommanderResult<SicherungsBericht>>
+        + Send
+        + Sync,
+>;
+
+/// Type-erased Kodierung einer hochgeladenen Datei fuer das Soundboard
+///
+/// Datei-Speicher und Audio-Codec kennt nur der Server; er setzt die
+/// Funktion nach dem Start per [`CommandExecutor::soundboard_kodierer_setzen`].
+/// Argumente sind der Speicherpfad der Datei und die erlaubte Hoechstdauer.
+pub type SoundKodiererFn = Arc<
+    dyn Fn(String, Duration) -> BoxFuture<'static, CommanderResult<KodierterSound>> + Send + Sync,
+>;
+
+/// Verbindung zu den live verbundenen Clients im Signaling-Dienst
+///
+/// Presence und TCP-Verbindungen leben im Signaling-Dienst; der Server setzt
+/// die Implementierung nach dem Start per [`CommandExecutor::signaling_setzen`].
+/// Ist der Client nicht verbunden, liefern die Methoden fuer einzelne Clients
+/// [`CommanderError::NichtGefunden`].
+pub trait SignalingNotifier: Send + Sync {
+    /// Benachrichtigt den Client, trennt seine Verbindung und raeumt
+    /// Presence- und Voice-Zustand ab
+    fn kicken(&self, user_id: Uuid, grund: Option<String>) -> BoxFuture<'_, CommanderResult<()>>;
+
+    /// Verschiebt den Client in einen anderen Kanal
+    fn verschieben(&self, user_id: Uuid, kanal_id: Uuid) -> BoxFuture<'_, CommanderResult<()>>;
+
+    /// Stellt dem Client eine Poke-Nachricht zu
+    fn anstupsen(&self, user_id: Uuid, nachricht: String) -> BoxFuture<'_, CommanderResult<()>>;
+
+    /// Meldet allen Clients die geaenderten Eigenschaften eines Kanals
+    fn kanal_geaendert(&self, kanal_id: Uuid) -> BoxFuture<'_, CommanderResult<()>>;
+
+    /// IP-Adresse der Control-Verbindung des Clients (fuer IP-Bans)
+    fn ip_adresse(&self, user_id: Uuid) -> CommanderResult<String>;
+}
+
 /// Einheitlicher Befehlsausführer
 ///
 /// Alle drei Interfaces (REST, TCP, gRPC) nutzen diese Struktur.
 /// Sie haelt Referenzen auf alle benoenigten Repositories und Services.
-pub struct CommandExecutor<U, C, P, B, A>
+pub struct CommandExecutor<U, C, P, B, A, F, T, E, Z>
 where
     U: UserRepository,
     C: ChannelRepository,
     P: PermissionRepository,
     B: BanRepository,
     A: AuditLogRepository,
+    F: FileRepository + SoundboardRepository + ChatMessageRepository,
+    T: ChannelTemplateRepository,
+    E: SettingsRepository + ServerStartRepository,
+    Z: ZeitplanRepository,
 {
     user_repo: Arc<U>,
     channel_repo: Arc<C>,
     permission_repo: Arc<P>,
     ban_repo: Arc<B>,
     audit_repo: Arc<A>,
-    #[allow(dead_code)]
+    file_repo: Arc<F>,
+    template_repo: Arc<T>,
+    zeitplan_repo: Arc<Z>,
+    /// Neustart-Protokoll (liegt beim Settings-Repository)
+    start_repo: Arc<E>,
     auth_service: Arc<AuthService<U>>,
-    #[allow(dead_code)]
     permission_service: Arc<PermissionService<P>>,
     #[allow(dead_code)]
     ban_service: Arc<BanService<B>>,
     // Hinweis: ban_repo wird direkt fuer den Ban-Check in ausfuehren() genutzt.
-    /// Server-Name (aus Konfiguration)
-    server_name: String,
+    /// Laufzeit-Einstellungen (Name, Willkommensnachricht, ...) aus `server_settings`
+    einstellungen: EinstellungsCache<E>,
     /// Server-Version
     server_version: String,
     /// Startzeit des Servers
     server_start: std::time::Instant,
+    /// Eintrag dieses Starts im Neustart-Protokoll (ohne: als sauber gemeldet)
+    neustart: OnceLock<ServerStartRecord>,
+    /// Grenzen fuer das Anlegen von Kanalbaeumen aus Vorlagen
+    kanal_grenzen: KanalbaumGrenzen,
+    /// Ereignisse fuer verbundene Clients (z.B. geaenderter Kanalbaum)
+    ereignisse: broadcast::Sender<CommanderEreignis>,
+    /// Sammel-Move im Signaling-Dienst (ohne: Befehl nicht verfuegbar)
+    client_verschieber: OnceLock<ClientVerschieberFn>,
+    /// Aktive Sprecher pro Kanal (ohne: Befehl nicht verfuegbar)
+    sprecher_abfrage: OnceLock<SprecherAbfrageFn>,
+    /// Voice-Diagnose pro Client (ohne: Befehl nicht verfuegbar)
+    voice_diagnose: OnceLock<VoiceDiagnoseFn>,
+    /// Notfall-Stummschaltung im Signaling-Dienst (ohne: Befehl nicht verfuegbar)
+    notfall_stumm: OnceLock<NotfallStummFn>,
+    /// Sofortige Auswertung der Alarmregeln (ohne: Befehl nicht verfuegbar)
+    alarm_pruefung: OnceLock<AlarmPruefungFn>,
+    /// Sicherung von Datenbank, Dateien und Konfiguration (ohne: Befehl nicht verfuegbar)
+    backup: OnceLock<BackupFn>,
+    /// Datenexport eines Kontos (ohne: Befehl nicht verfuegbar)
+    konto_export: OnceLock<KontoExportFn>,
+    /// Kontoloeschung (ohne: Befehl nicht verfuegbar)
+    konto_loeschen: OnceLock<KontoLoeschenFn>,
+    /// Inhaltsbereinigung (ohne: Befehl und Ban-Option nicht verfuegbar)
+    inhalte_bereinigen: OnceLock<InhalteBereinigenFn>,
+    /// Kick, Move und Poke verbundener Clients (ohne: Befehle nicht verfuegbar)
+    signaling: OnceLock<Arc<dyn SignalingNotifier>>,
+    /// Kodierung hochgeladener Dateien (ohne: Registrieren nicht verfuegbar)
+    soundboard_kodierer: OnceLock<SoundKodiererFn>,
+    /// Gepuffertes Audit-Log (ohne: jedes Ereignis wird direkt geschrieben)
+    audit_sink: OnceLock<Arc<dyn AuditSink>>,
+    /// Einkompilierte optionale Subsysteme des Servers (ohne: keine gemeldet)
+    features: OnceLock<Vec<String>>,
 }
 
-impl<U, C, P, B, A> CommandExecutor<U, C, P, B, A>
+impl<U, C, P, B, A, F, T, E, Z> CommandExecutor<U, C, P, B, A, F, T, E, Z>
 where
     U: UserRepository,
     C: ChannelRepository,
     P: PermissionRepository,
     B: BanRepository,
     A: AuditLogRepository,
+    F: FileRepository + SoundboardRepository + ChatMessageRepository,
+    T: ChannelTemplateRepository,
+    E: SettingsRepository + ServerStartRepository,
+    Z: ZeitplanRepository,
 {
     /// Erstellt einen neuen CommandExecutor
     #[allow(clippy::too_many_arguments)]
@@ -76,27 +268,181 @@ where
         permission_repo: Arc<P>,
         ban_repo: Arc<B>,
         audit_repo: Arc<A>,
+        file_repo: Arc<F>,
+        template_repo: Arc<T>,
+        settings_repo: Arc<E>,
+        zeitplan_repo: Arc<Z>,
         auth_service: Arc<AuthService<U>>,
         permission_service: Arc<PermissionService<P>>,
         ban_service: Arc<BanService<B>>,
-        server_name: String,
+        standard_einstellungen: ServerEinstellungen,
         server_version: String,
+        kanal_grenzen: KanalbaumGrenzen,
     ) -> Arc<Self> {
+        let (ereignisse, _) = broadcast::channel(EREIGNIS_KAPAZITAET);
         Arc::new(Self {
             user_repo,
             channel_repo,
             permission_repo,
             ban_repo,
             audit_repo,
+            file_repo,
+            template_repo,
+            zeitplan_repo,
             auth_service,
             permission_service,
             ban_service,
-            server_name,
+            start_repo: Arc::clone(&settings_repo),
+            einstellungen: EinstellungsCache::neu(settings_repo, standard_einstellungen),
             server_version,
             server_start: std::time::Instant::now(),
+            neustart: OnceLock::new(),
+            kanal_grenzen,
+            ereignisse,
+            client_verschieber: OnceLock::new(),
+            sprecher_abfrage: OnceLock::new(),
+            voice_diagnose: OnceLock::new(),
+            notfall_stumm: OnceLock::new(),
+            alarm_pruefung: OnceLock::new(),
+            backup: OnceLock::new(),
+            konto_export: OnceLock::new(),
+            konto_loeschen: OnceLock::new(),
+            inhalte_bereinigen: OnceLock::new(),
+            signaling: OnceLock::new(),
+            soundboard_kodierer: OnceLock::new(),
+            audit_sink: OnceLock::new(),
+            features: OnceLock::new(),
         })
     }
 
+    /// Meldet, mit welchen optionalen Subsystemen der Server gebaut wurde (nur einmal moeglich)
+    pub fn features_setzen(&self, features: Vec<String>) {
+        if self.features.set(features).is_err() {
+            tracing::warn!("Server-Features bereits gesetzt");
+        }
+    }
+
+    /// Meldet den Eintrag dieses Starts im Neustart-Protokoll (nur einmal moeglich)
+    pub fn neustart_setzen(&self, start: ServerStartRecord) {
+        if self.neustart.set(start).is_err() {
+            tracing::warn!("Neustart-Eintrag bereits gesetzt");
+        }
+    }
+
+    /// Verbindet den Sammel-Move mit dem Signaling-Dienst (nur einmal moeglich)
+    pub fn client_verschieber_setzen(&self, verschieber: ClientVerschieberFn) {
+        if self.client_verschieber.set(verschieber).is_err() {
+            tracing::warn!("Client-Verschieber bereits gesetzt");
+        }
+    }
+
+    /// Verbindet die Sprecher-Abfrage mit dem Signaling-Dienst (nur einmal moeglich)
+    pub fn sprecher_abfrage_setzen(&self, abfrage: SprecherAbfrageFn) {
+        if self.sprecher_abfrage.set(abfrage).is_err() {
+            tracing::warn!("Sprecher-Abfrage bereits gesetzt");
+        }
+    }
+
+    /// Verbindet die Voice-Diagnose mit dem Voice-Dienst (nur einmal moeglich)
+    pub fn voice_diagnose_setzen(&self, diagnose: VoiceDiagnoseFn) {
+        if self.voice_diagnose.set(diagnose).is_err() {
+            tracing::warn!("Voice-Diagnose bereits gesetzt");
+        }
+    }
+
+    /// Verbindet die Notfall-Stummschaltung mit dem Signaling-Dienst (nur einmal moeglich)
+    pub fn notfall_stumm_setzen(&self, schalter: NotfallStummFn) {
+        if self.notfall_stumm.set(schalter).is_err() {
+            tracing::warn!("Notfall-Stummschaltung bereits gesetzt");
+        }
+    }
+
+    /// Verbindet die Alarmregeln des Servers (nur einmal moeglich)
+    pub fn alarm_pruefung_setzen(&self, pruefung: AlarmPruefungFn) {
+        if self.alarm_pruefung.set(pruefung).is_err() {
+            tracing::warn!("Alarm-Pruefung bereits gesetzt");
+        }
+    }
+
+    /// Verbindet die Sicherung mit den Datenquellen des Servers (nur einmal moeglich)
+    pub fn backup_setzen(&self, backup: BackupFn) {
+        if self.backup.set(backup).is_err() {
+            tracing::warn!("Sicherung bereits gesetzt");
+        }
+    }
+
+    /// Verbindet den Datenexport mit dem Kontodienst des Servers (nur einmal moeglich)
+    pub fn konto_export_setzen(&self, export: KontoExportFn) {
+        if self.konto_export.set(export).is_err() {
+            tracing::warn!("Konto-Export bereits gesetzt");
+        }
+    }
+
+    /// Verbindet die Kontoloeschung mit dem Kontodienst des Servers (nur einmal moeglich)
+    pub fn konto_loeschen_setzen(&self, loeschen: KontoLoeschenFn) {
+        if self.konto_loeschen.set(loeschen).is_err() {
+            tracing::warn!("Kontoloeschung bereits gesetzt");
+        }
+    }
+
+    /// Verbindet die Inhaltsbereinigung mit dem Signaling-Dienst (nur einmal moeglich)
+    pub fn inhalte_bereinigen_setzen(&self, bereinigen: InhalteBereinigenFn) {
+        if self.inhalte_bereinigen.set(bereinigen).is_err() {
+            tracing::warn!("Inhaltsbereinigung bereits gesetzt");
+        }
+    }
+
+    /// Verbindet Kick, Move und Poke mit dem Signaling-Dienst (nur einmal moeglich)
+    pub fn signaling_setzen(&self, signaling: Arc<dyn SignalingNotifier>) {
+        if self.signaling.set(signaling).is_err() {
+            tracing::warn!("Signaling-Anbindung bereits gesetzt");
+        }
+    }
+
+    /// Verbindet das Soundboard mit Datei-Speicher und Audio-Codec (nur einmal moeglich)
+    pub fn soundboard_kodierer_setzen(&self, kodierer: SoundKodiererFn) {
+        if self.soundboard_kodierer.set(kodierer).is_err() {
+            tracing::warn!("Soundboard-Kodierer bereits gesetzt");
+        }
+    }
+
+    /// Leitet Audit-Ereignisse ueber einen Sink (nur einmal moeglich)
+    pub fn audit_sink_setzen(&self, sink: Arc<dyn AuditSink>) {
+        if self.audit_sink.set(sink).is_err() {
+            tracing::warn!("Audit-Sink bereits gesetzt");
+        }
+    }
+
+    /// Abonniert die Ereignisse des Commanders (z.B. fuer Signaling-Broadcasts)
+    pub fn ereignisse_abonnieren(&self) -> broadcast::Receiver<CommanderEreignis> {
+        self.ereignisse.subscribe()
+    }
+
+    /// Meldet eine Alarm-Meldung als [`CommanderEreignis::Alarm`]
+    pub fn alarm_melden(&self, meldung: AlarmMeldung) {
+        let _ = self.ereignisse.send(CommanderEreignis::Alarm(meldung));
+    }
+
+    /// Protokolliert ein Ereignis ueber den Audit-Sink, ohne auf das Schreiben zu warten
+    async fn audit(&self, eintrag: NeuerAuditEintrag) -> CommanderResult<()> {
+        match self.audit_sink.get() {
+            Some(sink) => sink.protokollieren(eintrag),
+            None => self.audit_repo.log_events(&[eintrag]).await?,
+        }
+        Ok(())
+    }
+
+    /// Protokolliert ein sicherheitskritisches Ereignis (Bans, Berechtigungen)
+    ///
+    /// Kehrt erst zurueck, wenn das Ereignis gespeichert ist.
+    async fn audit_sicher(&self, eintrag: NeuerAuditEintrag) -> CommanderResult<()> {
+        match self.audit_sink.get() {
+            Some(sink) => sink.sicher_protokollieren(eintrag).await?,
+            None => self.audit_repo.log_events(&[eintrag]).await?,
+        }
+        Ok(())
+    }
+
     /// Prueft ob der Benutzer aktuell gebannt ist.
     async fn ban_pruefen(&self, session: &CommanderSession) -> CommanderResult<()> {
         let ban = self
@@ -112,9 +458,12 @@ where
     }
 
     /// Prueft ob die Session den erforderlichen Scope fuer den Befehl besitzt.
+    ///
+    /// "cmd:*" deckt nur die "cmd:"-Scopes ab, keine Admin-Scopes.
     fn scope_pruefen(&self, cmd: &Command, session: &CommanderSession) -> CommanderResult<()> {
         let erforderlich = cmd.erforderlicher_scope();
-        if !session.hat_scope(erforderlich) && !session.hat_scope("cmd:*") {
+        let wildcard = erforderlich.starts_with("cmd:") && session.hat_scope("cmd:*");
+        if !session.hat_scope(erforderlich) && !wildcard {
             return Err(CommanderError::NichtAutorisiert(format!(
                 "Scope '{erforderlich}' erforderlich"
             )));
@@ -141,11 +490,15 @@ where
         match cmd {
             // --- Server ---
             Command::ServerInfo => self.server_info().await,
+            Command::ServerHistorie { limit } => self.server_historie(limit).await,
             Command::ServerEdit {
                 name,
                 willkommensnachricht,
                 max_clients,
                 host_nachricht,
+                afk_timeout_sek,
+                afk_kanal_id,
+                chat_bearbeitungsfrist_sek,
             } => {
                 self.server_bearbeiten(
                     session,
@@ -153,13 +506,25 @@ where
                     willkommensnachricht,
                     max_clients,
                     host_nachricht,
+                    afk_timeout_sek,
+                    afk_kanal_id,
+                    chat_bearbeitungsfrist_sek,
                 )
                 .await
             }
             Command::ServerStop { grund } => self.server_stoppen(session, grund).await,
+            Command::BackupErstellen {
+                ziel_pfad,
+                include_files,
+            } => {
+                self.backup_erstellen(session, ziel_pfad, include_files)
+                    .await
+            }
+            Command::MotdSetzen { markdown } => self.motd_setzen(session, markdown).await,
+            Command::AlertRegelnTesten => self.alert_regeln_testen().await,
 
             // --- Kanaele ---
-            Command::KanalListe => self.kanal_liste().await,
+            Command::KanalListe { name, seite } => self.kanal_liste(name, seite).await,
             Command::KanalErstellen {
                 name,
                 parent_id,
@@ -186,14 +551,60 @@ where
                 thema,
                 max_clients,
                 sort_order,
+                langsammodus_sek,
+                bearbeitungsfrist_sek,
             } => {
-                self.kanal_bearbeiten(session, id, name, thema, max_clients, sort_order)
-                    .await
+                self.kanal_bearbeiten(
+                    session,
+                    id,
+                    name,
+                    thema,
+                    max_clients,
+                    sort_order,
+                    langsammodus_sek,
+                    bearbeitungsfrist_sek,
+                )
+                .await
             }
             Command::KanalLoeschen { id } => self.kanal_loeschen(session, id).await,
+            Command::KanalbaumAusVorlage {
+                vorlage_id,
+                parent_id,
+                name_prefix,
+            } => {
+                self.kanalbaum_aus_vorlage(session, vorlage_id, parent_id, name_prefix)
+                    .await
+            }
+            Command::KanalNotfallStumm {
+                kanal_id,
+                aktivieren,
+            } => {
+                self.kanal_notfall_stumm(NotfallStummAuftrag {
+                    aktor_id: session.benutzer.id,
+                    kanal_id,
+                    aktivieren,
+                })
+                .await
+            }
+
+            // --- Kanal-Vorlagen ---
+            Command::VorlageListe => self.vorlage_liste().await,
+            Command::VorlageErstellen {
+                name,
+                beschreibung,
+                definition,
+            } => {
+                self.vorlage_erstellen(session, name, beschreibung, definition)
+                    .await
+            }
+            Command::VorlageLoeschen { id } => self.vorlage_loeschen(session, id).await,
 
             // --- Clients ---
-            Command::ClientListe => self.client_liste().await,
+            Command::ClientListe {
+                kanal_id,
+                username,
+                seite,
+            } => self.client_liste(kanal_id, username, seite).await,
             Command::ClientKicken { client_id, grund } => {
                 self.client_kicken(session, client_id, grund).await
             }
@@ -202,18 +613,93 @@ where
                 dauer_secs,
                 grund,
                 ip_bannen,
+                inhalte_entfernen,
             } => {
-                self.client_bannen(session, client_id, dauer_secs, grund, ip_bannen)
-                    .await
+                self.client_bannen(
+                    session,
+                    client_id,
+                    dauer_secs,
+                    grund,
+                    ip_bannen,
+                    inhalte_entfernen,
+                )
+                .await
             }
             Command::ClientVerschieben {
                 client_id,
                 kanal_id,
             } => self.client_verschieben(session, client_id, kanal_id).await,
+            Command::AktiveSprecher { kanal_id } => self.aktive_sprecher(kanal_id),
+            Command::VoiceDiagnose { client_id } => self.voice_diagnose(client_id),
+            Command::ClientsVerschiebenAlle {
+                von_kanal_id,
+                nach_kanal_id,
+                nur_user_ids,
+                teilweise,
+            } => {
+                self.clients_alle_verschieben(SammelVerschiebung {
+                    aktor_id: session.benutzer.id,
+                    von_kanal_id,
+                    nach_kanal_id,
+                    nur_user_ids,
+                    teilweise,
+                })
+                .await
+            }
             Command::ClientPoken {
                 client_id,
                 nachricht,
             } => self.client_poken(session, client_id, nachricht).await,
+            Command::BenutzerInhalteBereinigen {
+                user_id,
+                zeitraum,
+                nur_kanal,
+                job_id,
+            } => {
+                if job_id.is_none() && zeitraum.is_zero() {
+                    return Err(CommanderError::UngueltigeEingabe(
+                        "Zeitraum fehlt (oder job_id zum Fortsetzen angeben)".into(),
+                    ));
+                }
+                self.benutzer_inhalte_bereinigen(BereinigungsAuftrag {
+                    aktor_id: session.benutzer.id,
+                    benutzer_id: user_id,
+                    zeitraum,
+                    nur_kanal,
+                    job_id,
+                })
+                .await
+            }
+
+            // --- Konten ---
+            Command::KontoExport { benutzer_id } => {
+                self.konto_exportieren(session, benutzer_id).await
+            }
+            Command::KontoLoeschen { benutzer_id } => {
+                self.konto_loeschen(session, benutzer_id).await
+            }
+
+            // --- Benutzerverwaltung ---
+            Command::BenutzerListe {
+                filter,
+                limit,
+                offset,
+            } => self.benutzer_liste(filter, limit, offset).await,
+            Command::BenutzerPasswortSetzen {
+                user_id,
+                neues_passwort,
+            } => {
+                self.benutzer_passwort_setzen(session, user_id, neues_passwort)
+                    .await
+            }
+            Command::BenutzerDeaktivieren {
+                user_id,
+                deaktiviert,
+            } => {
+                self.benutzer_deaktivieren(session, user_id, deaktiviert)
+                    .await
+            }
+            Command::BenutzerLoeschen { user_id } => self.benutzer_loeschen(session, user_id).await,
 
             // --- Berechtigungen ---
             Command::BerechtigungListe { ziel, scope } => {
@@ -236,10 +722,41 @@ where
                 self.berechtigung_entfernen(session, ziel, permission, scope)
                     .await
             }
+            Command::BerechtigungEffektiv { user_id, kanal_id } => {
+                self.berechtigung_effektiv(user_id, kanal_id).await
+            }
 
             // --- Dateien ---
             Command::DateiListe { kanal_id } => self.datei_liste(kanal_id).await,
             Command::DateiLoeschen { datei_id } => self.datei_loeschen(session, datei_id).await,
+            Command::DateiZugriffe {
+                datei_id,
+                benutzer_id,
+                von,
+                bis,
+                limit,
+                offset,
+            } => {
+                self.datei_zugriffe(session, datei_id, benutzer_id, von, bis, limit, offset)
+                    .await
+            }
+
+            // --- Chat ---
+            Command::NachrichtInfo { nachricht_id } => {
+                self.nachricht_info(session, nachricht_id).await
+            }
+
+            // --- Soundboard ---
+            Command::SoundboardListe { kanal_id } => self.soundboard_liste(kanal_id).await,
+            Command::SoundboardRegistrieren {
+                name,
+                datei_id,
+                kanal_id,
+            } => {
+                self.soundboard_registrieren(session, name, datei_id, kanal_id)
+                    .await
+            }
+            Command::SoundboardEntfernen { id } => self.soundboard_entfernen(session, id).await,
 
             // --- Logs ---
             Command::LogAbfragen {
@@ -247,6 +764,20 @@ where
                 offset,
                 aktion_filter,
             } => self.log_abfragen(limit, offset, aktion_filter).await,
+
+            // --- Zeitplaner ---
+            Command::ZeitplanListe => self.zeitplan_liste().await,
+            Command::ZeitplanErstellen {
+                name,
+                aktion,
+                einmalig,
+                cron,
+                zeitzone,
+            } => {
+                self.zeitplan_erstellen(session, name, aktion, einmalig, cron, zeitzone)
+                    .await
+            }
+            Command::ZeitplanAbbrechen { id } => self.zeitplan_abbrechen(session, id).await,
         }
     }
 
@@ -256,38 +787,104 @@ where
 
     async fn server_info(&self) -> CommanderResult<Response> {
         let benutzer_anzahl = self.user_repo.list(true).await?.len() as u32;
+        let einstellungen = self.einstellungen.aktuell().await?;
         Ok(Response::ServerInfo(ServerInfoResponse {
-            name: self.server_name.clone(),
-            willkommensnachricht: String::new(),
-            max_clients: 100,
+            name: einstellungen.name,
+            willkommensnachricht: einstellungen.willkommensnachricht.unwrap_or_default(),
+            max_clients: einstellungen.max_clients,
             aktuelle_clients: benutzer_anzahl,
             version: self.server_version.clone(),
             uptime_secs: self.server_start.elapsed().as_secs(),
+            host_nachricht: einstellungen.host_nachricht,
+            features: self.features.get().cloned().unwrap_or_default(),
+            letzter_shutdown_unsauber: self.neustart.get().is_some_and(|s| !s.clean_shutdown),
         }))
     }
 
+    async fn server_historie(&self, limit: u32) -> CommanderResult<Response> {
+        let starts = self.start_repo.list_recent(i64::from(limit)).await?;
+        Ok(Response::ServerHistorie(
+            starts
+                .into_iter()
+                .map(|s| ServerStartInfo {
+                    gestartet_um: s.started_at,
+                    version: s.version,
+                    sauber_beendet: s.clean_shutdown,
+                    config_hash: s.config_hash,
+                })
+                .collect(),
+        ))
+    }
+
     async fn server_bearbeiten(
         &self,
         session: &CommanderSession,
         name: Option<String>,
-        _willkommensnachricht: Option<String>,
-        _max_clients: Option<u32>,
-        _host_nachricht: Option<String>,
+        willkommensnachricht: Option<String>,
+        max_clients: Option<u32>,
+        host_nachricht: Option<String>,
+        afk_timeout_sek: Option<u32>,
+        afk_kanal_id: Option<Uuid>,
+        chat_bearbeitungsfrist_sek: Option<u32>,
     ) -> CommanderResult<Response> {
         if !session.hat_scope("admin:server:write") {
             return Err(CommanderError::NichtAutorisiert(
                 "Scope 'admin:server:write' erforderlich".into(),
             ));
         }
-        self.audit_repo
-            .log_event(
-                Some(session.benutzer.id),
-                "server.bearbeitet",
-                Some("server"),
-                None,
-                serde_json::json!({ "name": name }),
-            )
-            .await?;
+        if let Some(kanal_id) = afk_kanal_id {
+            self.channel_repo
+                .get_by_id(kanal_id)
+                .await?
+                .ok_or_else(|| CommanderError::NichtGefunden(format!("Kanal {kanal_id}")))?;
+        }
+        let aenderung = EinstellungsAenderung {
+            name,
+            willkommensnachricht,
+            max_clients,
+            host_nachricht,
+            chat_bearbeitungsfrist_sek,
+        };
+        aenderung.pruefen().map_err(eingabe_fehler)?;
+
+        self.audit(NeuerAuditEintrag::neu(
+            Some(session.benutzer.id),
+            "server.bearbeitet",
+            Some("server"),
+            None,
+            serde_json::json!({
+                "name": aenderung.name,
+                "willkommensnachricht": aenderung.willkommensnachricht,
+                "max_clients": aenderung.max_clients,
+                "host_nachricht": aenderung.host_nachricht,
+                "afk_timeout_sek": afk_timeout_sek,
+                "afk_kanal_id": afk_kanal_id,
+                "chat_bearbeitungsfrist_sek": aenderung.chat_bearbeitungsfrist_sek,
+            }),
+        ))
+        .await?;
+
+        if !aenderung.ist_leer() {
+            let einstellungen = self
+                .einstellungen
+                .aendern(&aenderung)
+                .await
+                .map_err(eingabe_fehler)?;
+            let _ = self
+                .ereignisse
+                .send(CommanderEreignis::ServerEinstellungenGeaendert(
+                    einstellungen,
+                ));
+        }
+
+        if afk_timeout_sek.is_some() || afk_kanal_id.is_some() {
+            let _ = self
+                .ereignisse
+                .send(CommanderEreignis::AfkRichtlinieGeaendert {
+                    timeout_sek: afk_timeout_sek,
+                    kanal_id: afk_kanal_id,
+                });
+        }
         Ok(Response::Ok)
     }
 
@@ -306,38 +903,125 @@ where
             grund = ?grund,
             "Server-Stopp angefordert"
         );
-        self.audit_repo
-            .log_event(
-                Some(session.benutzer.id),
-                "server.gestoppt",
-                Some("server"),
-                None,
-                serde_json::json!({ "grund": grund }),
-            )
-            .await?;
+        self.audit(NeuerAuditEintrag::neu(
+            Some(session.benutzer.id),
+            "server.gestoppt",
+            Some("server"),
+            None,
+            serde_json::json!({ "grund": grund }),
+        ))
+        .await?;
         Ok(Response::Ok)
     }
 
+    /// Startet eine Sicherung im Hintergrund
+    ///
+    /// Das Ziel wird vorab geprueft, damit ein belegtes Verzeichnis sofort
+    /// abgelehnt wird. Fortschritt, Abschluss und Fehler kommen als
+    /// [`CommanderEreignis::Sicherung`] – eine grosse Sicherung dauert
+    /// laenger als das Zeitlimit eines Befehls.
+    async fn backup_erstellen(
+        &self,
+        session: &CommanderSession,
+        ziel_pfad: String,
+        include_files: bool,
+    ) -> CommanderResult<Response> {
+        let backup = self
+            .backup
+            .get()
+            .ok_or_else(|| CommanderError::Intern(anyhow::anyhow!("Sicherung nicht verfuegbar")))?;
+        if ziel_pfad.trim().is_empty() {
+            return Err(CommanderError::UngueltigeEingabe(
+                "Zielpfad darf nicht leer sein".into(),
+            ));
+        }
+        let ziel = PathBuf::from(&ziel_pfad);
+        sicherung::ziel_pruefen(&ziel).await?;
+
+        self.audit(NeuerAuditEintrag::neu(
+            Some(session.benutzer.id),
+            "server.sicherung_gestartet",
+            Some("server"),
+            None,
+            serde_json::json!({ "ziel": ziel_pfad, "dateien": include_files }),
+        ))
+        .await?;
+
+        let ereignisse = self.ereignisse.clone();
+        let fortschritt: FortschrittFn = Arc::new(move |fortschritt| {
+            let _ = ereignisse.send(CommanderEreignis::Sicherung(fortschritt));
+        });
+        let arbeit = backup(SicherungsAuftrag {
+            ziel,
+            dateien_einschliessen: include_files,
+            fortschritt,
+        });
+        let aktor = session.benutzer.username.clone();
+        tokio::spawn(async move {
+            if let Err(e) = arbeit.await {
+                tracing::error!(aktor = %aktor, fehler = %e, "Sicherung fehlgeschlagen");
+            }
+        });
+        Ok(Response::BackupGestartet { ziel_pfad })
+    }
+
+    /// Speichert die Nachricht des Tages und meldet eine neue Version
+    async fn motd_setzen(
+        &self,
+        session: &CommanderSession,
+        markdown: String,
+    ) -> CommanderResult<Response> {
+        let (motd, geaendert) = self
+            .einstellungen
+            .motd_setzen(&markdown)
+            .await
+            .map_err(eingabe_fehler)?;
+        if !geaendert {
+            return Ok(Response::Motd(motd));
+        }
+
+        self.audit(NeuerAuditEintrag::neu(
+            Some(session.benutzer.id),
+            "server.motd_gesetzt",
+            Some("server"),
+            None,
+            serde_json::json!({ "version": motd.version, "bytes": motd.markdown.len() }),
+        ))
+        .await?;
+        let _ = self
+            .ereignisse
+            .send(CommanderEreignis::MotdGeaendert(motd.clone()));
+        Ok(Response::Motd(motd))
+    }
+
+    /// Wertet alle Alarmregeln sofort aus
+    async fn alert_regeln_testen(&self) -> CommanderResult<Response> {
+        let pruefung = self.alarm_pruefung.get().ok_or_else(|| {
+            CommanderError::Intern(anyhow::anyhow!("Alarmierung nicht verfuegbar"))
+        })?;
+        Ok(Response::AlarmRegeln(pruefung().await))
+    }
+
     // -----------------------------------------------------------------------
     // Kanal-Befehle
     // -----------------------------------------------------------------------
 
-    async fn kanal_liste(&self) -> CommanderResult<Response> {
-        let kanaele = self.channel_repo.list().await?;
-        let infos: Vec<KanalInfo> = kanaele
-            .into_iter()
-            .map(|k| KanalInfo {
-                id: k.id,
-                name: k.name,
-                parent_id: k.parent_id,
-                thema: k.topic,
-                max_clients: k.max_clients,
-                aktuelle_clients: 0,
-                sort_order: k.sort_order,
-                passwort_geschuetzt: k.password_hash.is_some(),
-            })
-            .collect();
-        Ok(Response::KanalListe(infos))
+    async fn kanal_liste(
+        &self,
+        name: Option<String>,
+        seite: SeitenAnfrage,
+    ) -> CommanderResult<Response> {
+        let filter = KanalFilter {
+            name_contains: name.filter(|n| !n.is_empty()),
+            limit: Some(i64::from(seite.groesse)),
+            offset: Some(seite.offset() as i64),
+        };
+        let gesamt = self.channel_repo.count_filtered(filter.clone()).await?;
+        let kanaele = self.channel_repo.list_filtered(filter).await?;
+        Ok(Response::KanalListe(KanalSeite {
+            kanaele: kanaele.into_iter().map(kanal_zu_info).collect(),
+            seite: seite.info(gesamt.max(0) as u64),
+        }))
     }
 
     async fn kanal_erstellen(
@@ -355,10 +1039,10 @@ where
                 "Kanalname darf nicht leer sein".into(),
             ));
         }
-        let passwort_hash = passwort.map(|p| {
-            // In Produktion: Argon2-Hash; hier vereinfacht
-            format!("hash:{p}")
-        });
+        let passwort_hash = passwort
+            .as_deref()
+            .map(speakeasy_auth::passwort_hashen)
+            .transpose()?;
         let kanal = self
             .channel_repo
             .create(NeuerKanal {
@@ -372,15 +1056,14 @@ where
                 channel_type: speakeasy_db::models::KanalTyp::Voice,
             })
             .await?;
-        self.audit_repo
-            .log_event(
-                Some(session.benutzer.id),
-                "kanal.erstellt",
-                Some("channel"),
-                Some(&kanal.id.to_string()),
-                serde_json::json!({ "name": kanal.name }),
-            )
-            .await?;
+        self.audit(NeuerAuditEintrag::neu(
+            Some(session.benutzer.id),
+            "kanal.erstellt",
+            Some("channel"),
+            Some(&kanal.id.to_string()),
+            serde_json::json!({ "name": kanal.name }),
+        ))
+        .await?;
         Ok(Response::Kanal(KanalInfo {
             id: kanal.id,
             name: kanal.name,
@@ -390,9 +1073,12 @@ where
             aktuelle_clients: 0,
             sort_order: kanal.sort_order,
             passwort_geschuetzt: kanal.password_hash.is_some(),
+            langsammodus_sek: kanal.slow_mode_secs,
+            bearbeitungsfrist_sek: kanal.edit_window_secs,
         }))
     }
 
+    #[allow(clippy::too_many_arguments)]
     async fn kanal_bearbeiten(
         &self,
         session: &CommanderSession,
@@ -401,7 +1087,22 @@ where
         thema: Option<Option<String>>,
         max_clients: Option<i64>,
         sort_order: Option<i64>,
+        langsammodus_sek: Option<u32>,
+        bearbeitungsfrist_sek: Option<Option<u32>>,
     ) -> CommanderResult<Response> {
+        if langsammodus_sek.is_some_and(|s| s > MAX_LANGSAMMODUS_SEK) {
+            return Err(CommanderError::UngueltigeEingabe(format!(
+                "Langsam-Modus hoechstens {MAX_LANGSAMMODUS_SEK} Sekunden"
+            )));
+        }
+        if bearbeitungsfrist_sek
+            .flatten()
+            .is_some_and(|s| s > MAX_BEARBEITUNGSFRIST_SEK)
+        {
+            return Err(CommanderError::UngueltigeEingabe(format!(
+                "Bearbeitungsfrist hoechstens {MAX_BEARBEITUNGSFRIST_SEK} Sekunden"
+            )));
+        }
         let kanal = self
             .channel_repo
             .update(
@@ -411,19 +1112,31 @@ where
                     topic: thema,
                     max_clients,
                     sort_order,
+                    slow_mode_secs: langsammodus_sek.map(i64::from),
+                    edit_window_secs: bearbeitungsfrist_sek.map(|s| s.map(i64::from)),
                     ..Default::default()
                 },
             )
             .await?;
-        self.audit_repo
-            .log_event(
-                Some(session.benutzer.id),
-                "kanal.bearbeitet",
-                Some("channel"),
-                Some(&id.to_string()),
-                serde_json::json!({ "name": name }),
-            )
-            .await?;
+        self.audit(NeuerAuditEintrag::neu(
+            Some(session.benutzer.id),
+            "kanal.bearbeitet",
+            Some("channel"),
+            Some(&id.to_string()),
+            serde_json::json!({
+                "name": name,
+                "langsammodus_sek": langsammodus_sek,
+                "bearbeitungsfrist_sek": bearbeitungsfrist_sek,
+            }),
+        ))
+        .await?;
+        // Verbundene Clients erfahren die Aenderung sofort; ohne Anbindung
+        // (z.B. Commander allein) lesen sie den Kanal beim naechsten Laden
+        if let Some(signaling) = self.signaling.get() {
+            if let Err(e) = signaling.kanal_geaendert(id).await {
+                tracing::warn!(kanal = %id, fehler = %e, "Kanal-Aenderung nicht verteilt");
+            }
+        }
         Ok(Response::Kanal(KanalInfo {
             id: kanal.id,
             name: kanal.name,
@@ -433,6 +1146,8 @@ where
             aktuelle_clients: 0,
             sort_order: kanal.sort_order,
             passwort_geschuetzt: kanal.password_hash.is_some(),
+            langsammodus_sek: kanal.slow_mode_secs,
+            bearbeitungsfrist_sek: kanal.edit_window_secs,
         }))
     }
 
@@ -447,80 +1162,256 @@ where
                 "Kanal {id} nicht gefunden"
             )));
         }
-        self.audit_repo
-            .log_event(
-                Some(session.benutzer.id),
-                "kanal.geloescht",
-                Some("channel"),
-                Some(&id.to_string()),
-                serde_json::json!({}),
-            )
-            .await?;
+        self.audit(NeuerAuditEintrag::neu(
+            Some(session.benutzer.id),
+            "kanal.geloescht",
+            Some("channel"),
+            Some(&id.to_string()),
+            serde_json::json!({}),
+        ))
+        .await?;
         Ok(Response::Ok)
     }
 
+    async fn kanalbaum_aus_vorlage(
+        &self,
+        session: &CommanderSession,
+        vorlage_id: Uuid,
+        parent_id: Option<Uuid>,
+        name_prefix: Option<String>,
+    ) -> CommanderResult<Response> {
+        let kanaele = self
+            .template_repo
+            .instantiate(
+                vorlage_id,
+                parent_id,
+                name_prefix.as_deref(),
+                self.kanal_grenzen,
+            )
+            .await
+            .map_err(eingabe_fehler)?;
+        let wurzel = kanaele
+            .first()
+            .ok_or_else(|| CommanderError::Intern(anyhow::anyhow!("Leerer Kanalbaum")))?;
+
+        self.audit(NeuerAuditEintrag::neu(
+            Some(session.benutzer.id),
+            "kanal.baum_erstellt",
+            Some("channel"),
+            Some(&wurzel.id.to_string()),
+            serde_json::json!({
+                "vorlage_id": vorlage_id,
+                "anzahl": kanaele.len(),
+            }),
+        ))
+        .await?;
+
+        // Ein einziges Ereignis fuer den gesamten Teilbaum
+        let _ = self.ereignisse.send(CommanderEreignis::KanalbaumGeaendert {
+            wurzel_id: wurzel.id,
+            parent_id,
+            kanaele: kanaele.iter().map(|k| k.id).collect(),
+        });
+
+        Ok(Response::Kanalbaum(
+            kanaele.into_iter().map(kanal_zu_info).collect(),
+        ))
+    }
+
     // -----------------------------------------------------------------------
-    // Client-Befehle (ephemere Daten, Stub-Implementierung)
+    // Kanal-Vorlagen
     // -----------------------------------------------------------------------
 
-    async fn client_liste(&self) -> CommanderResult<Response> {
-        // In Produktion: aus Voice-State/Presence laden
-        Ok(Response::ClientListe(vec![]))
+    async fn vorlage_liste(&self) -> CommanderResult<Response> {
+        let vorlagen = self.template_repo.list().await?;
+        Ok(Response::VorlageListe(
+            vorlagen.into_iter().map(vorlage_zu_info).collect(),
+        ))
     }
 
-    async fn client_kicken(
+    async fn vorlage_erstellen(
         &self,
         session: &CommanderSession,
-        client_id: Uuid,
-        grund: Option<String>,
+        name: String,
+        beschreibung: Option<String>,
+        definition: serde_json::Value,
     ) -> CommanderResult<Response> {
-        // In Produktion: Signaling-Service benachrichtigen
-        tracing::info!(
-            aktor = %session.benutzer.username,
-            client = %client_id,
-            grund = ?grund,
-            "Client wird gekickt"
-        );
-        self.audit_repo
-            .log_event(
-                Some(session.benutzer.id),
-                "client.gekickt",
-                Some("user"),
-                Some(&client_id.to_string()),
-                serde_json::json!({ "grund": grund }),
-            )
-            .await?;
-        Ok(Response::Ok)
+        let root: VorlagenKnoten = serde_json::from_value(definition).map_err(|e| {
+            CommanderError::UngueltigeEingabe(format!("Ungueltige Vorlagen-Definition: {e}"))
+        })?;
+        let vorlage = self
+            .template_repo
+            .create(NeueKanalVorlage {
+                name: &name,
+                description: beschreibung.as_deref(),
+                root,
+                created_by: Some(session.benutzer.id),
+            })
+            .await
+            .map_err(eingabe_fehler)?;
+        self.audit(NeuerAuditEintrag::neu(
+            Some(session.benutzer.id),
+            "vorlage.erstellt",
+            Some("channel_template"),
+            Some(&vorlage.id.to_string()),
+            serde_json::json!({ "name": vorlage.name }),
+        ))
+        .await?;
+        Ok(Response::Vorlage(vorlage_zu_info(vorlage)))
     }
 
-    async fn client_bannen(
+    async fn vorlage_loeschen(
         &self,
         session: &CommanderSession,
-        client_id: Uuid,
-        dauer_secs: Option<u64>,
-        grund: Option<String>,
-        _ip_bannen: bool,
+        id: Uuid,
     ) -> CommanderResult<Response> {
-        let laeuft_ab = dauer_secs.map(|d| Utc::now() + chrono::Duration::seconds(d as i64));
-        self.ban_repo
-            .create(NeuerBan {
-                user_id: Some(client_id),
-                ip: None,
-                reason: grund.as_deref().unwrap_or("Kein Grund angegeben"),
-                banned_by: Some(session.benutzer.id),
+        let geloescht = self
+            .template_repo
+            .delete(id)
+            .await
+            .map_err(eingabe_fehler)?;
+        if !geloescht {
+            return Err(CommanderError::NichtGefunden(format!(
+                "Vorlage {id} nicht gefunden"
+            )));
+        }
+        self.audit(NeuerAuditEintrag::neu(
+            Some(session.benutzer.id),
+            "vorlage.geloescht",
+            Some("channel_template"),
+            Some(&id.to_string()),
+            serde_json::json!({}),
+        ))
+        .await?;
+        Ok(Response::Ok)
+    }
+
+    // -----------------------------------------------------------------------
+    // Client-Befehle (ephemere Daten leben im Signaling-Dienst)
+    // -----------------------------------------------------------------------
+
+    async fn client_liste(
+        &self,
+        kanal_id: Option<Uuid>,
+        username: Option<String>,
+        seite: SeitenAnfrage,
+    ) -> CommanderResult<Response> {
+        // In Produktion: aus Voice-State/Presence laden
+        let clients = Vec::new();
+        Ok(Response::ClientListe(client_seite(
+            clients,
+            kanal_id,
+            username.as_deref(),
+            seite,
+        )))
+    }
+
+    /// Anbindung an den Signaling-Dienst fuer Befehle an verbundene Clients
+    fn signaling(&self) -> CommanderResult<&dyn SignalingNotifier> {
+        self.signaling.get().map(|s| s.as_ref()).ok_or_else(|| {
+            CommanderError::Intern(anyhow::anyhow!("Signaling-Anbindung nicht verfuegbar"))
+        })
+    }
+
+    async fn client_kicken(
+        &self,
+        session: &CommanderSession,
+        client_id: Uuid,
+        grund: Option<String>,
+    ) -> CommanderResult<Response> {
+        self.signaling()?.kicken(client_id, grund.clone()).await?;
+        tracing::info!(
+            aktor = %session.benutzer.username,
+            client = %client_id,
+            grund = ?grund,
+            "Client gekickt"
+        );
+        self.audit(NeuerAuditEintrag::neu(
+            Some(session.benutzer.id),
+            "client.gekickt",
+            Some("user"),
+            Some(&client_id.to_string()),
+            serde_json::json!({ "grund": grund }),
+        ))
+        .await?;
+        Ok(Response::Ok)
+    }
+
+    async fn client_bannen(
+        &self,
+        session: &CommanderSession,
+        client_id: Uuid,
+        dauer_secs: Option<u64>,
+        grund: Option<String>,
+        ip_bannen: bool,
+        inhalte_entfernen: Option<Duration>,
+    ) -> CommanderResult<Response> {
+        // Die IP kennt nur die Presence; offline Clients lassen sich nicht per IP bannen
+        let ip = if ip_bannen {
+            Some(self.signaling()?.ip_adresse(client_id)?)
+        } else {
+            None
+        };
+        let laeuft_ab = dauer_secs.map(|d| Utc::now() + chrono::Duration::seconds(d as i64));
+        self.ban_repo
+            .create(NeuerBan {
+                user_id: Some(client_id),
+                ip: ip.as_deref(),
+                reason: grund.as_deref().unwrap_or("Kein Grund angegeben"),
+                banned_by: Some(session.benutzer.id),
                 expires_at: laeuft_ab,
             })
             .await?;
-        self.audit_repo
-            .log_event(
-                Some(session.benutzer.id),
-                "client.gebannt",
-                Some("user"),
-                Some(&client_id.to_string()),
-                serde_json::json!({ "grund": grund, "dauer_secs": dauer_secs }),
-            )
-            .await?;
-        Ok(Response::Ok)
+        self.audit_sicher(NeuerAuditEintrag::neu(
+            Some(session.benutzer.id),
+            "client.gebannt",
+            Some("user"),
+            Some(&client_id.to_string()),
+            serde_json::json!({ "grund": grund, "dauer_secs": dauer_secs, "ip": ip }),
+        ))
+        .await?;
+        // Eine noch verbundene Sitzung endet sofort samt Voice-Zustand; ohne
+        // Anbindung greift der Ban bei der naechsten Anmeldung
+        if let Some(signaling) = self.signaling.get() {
+            match signaling.kicken(client_id, grund.clone()).await {
+                Ok(()) | Err(CommanderError::NichtGefunden(_)) => {}
+                Err(e) => {
+                    tracing::warn!(client = %client_id, fehler = %e, "Gebannter Client nicht getrennt")
+                }
+            }
+        }
+        match inhalte_entfernen {
+            Some(zeitraum) => {
+                self.benutzer_inhalte_bereinigen(BereinigungsAuftrag {
+                    aktor_id: session.benutzer.id,
+                    benutzer_id: client_id,
+                    zeitraum,
+                    nur_kanal: None,
+                    job_id: None,
+                })
+                .await
+            }
+            None => Ok(Response::Ok),
+        }
+    }
+
+    /// Entfernt Nachrichten und Dateien eines Benutzers ueber den Signaling-Dienst
+    async fn benutzer_inhalte_bereinigen(
+        &self,
+        auftrag: BereinigungsAuftrag,
+    ) -> CommanderResult<Response> {
+        let bereinigen = self.inhalte_bereinigen.get().ok_or_else(|| {
+            CommanderError::Intern(anyhow::anyhow!("Inhaltsbereinigung nicht verfuegbar"))
+        })?;
+        let ergebnis = bereinigen(auftrag).await?;
+        tracing::info!(
+            job_id = %ergebnis.job_id,
+            benutzer = %ergebnis.benutzer_id,
+            nachrichten = ergebnis.nachrichten,
+            dateien = ergebnis.dateien,
+            "Inhalte bereinigt"
+        );
+        Ok(Response::InhalteBereinigt(ergebnis))
     }
 
     async fn client_verschieben(
@@ -529,25 +1420,77 @@ where
         client_id: Uuid,
         kanal_id: Uuid,
     ) -> CommanderResult<Response> {
-        // In Produktion: Signaling-Service benachrichtigen
+        self.signaling()?.verschieben(client_id, kanal_id).await?;
         tracing::info!(
             aktor = %session.benutzer.username,
             client = %client_id,
             ziel_kanal = %kanal_id,
-            "Client wird verschoben"
+            "Client verschoben"
         );
-        self.audit_repo
-            .log_event(
-                Some(session.benutzer.id),
-                "client.verschoben",
-                Some("user"),
-                Some(&client_id.to_string()),
-                serde_json::json!({ "ziel_kanal": kanal_id }),
-            )
-            .await?;
+        self.audit(NeuerAuditEintrag::neu(
+            Some(session.benutzer.id),
+            "client.verschoben",
+            Some("user"),
+            Some(&client_id.to_string()),
+            serde_json::json!({ "ziel_kanal": kanal_id }),
+        ))
+        .await?;
         Ok(Response::Ok)
     }
 
+    /// Sammel-Move ueber den Signaling-Dienst
+    ///
+    /// Berechtigungen, Move-Power und Kapazitaet prueft der Signaling-Dienst
+    /// gegen die aktuelle Presence; dort entsteht auch der Audit-Eintrag
+    /// mit der vollstaendigen Liste.
+    async fn clients_alle_verschieben(
+        &self,
+        auftrag: SammelVerschiebung,
+    ) -> CommanderResult<Response> {
+        if auftrag.von_kanal_id == auftrag.nach_kanal_id {
+            return Err(CommanderError::UngueltigeEingabe(
+                "Quell- und Zielkanal sind identisch".into(),
+            ));
+        }
+        let verschieber = self.client_verschieber.get().ok_or_else(|| {
+            CommanderError::Intern(anyhow::anyhow!("Sammel-Move nicht verfuegbar"))
+        })?;
+        let ergebnis = verschieber(auftrag).await?;
+        Ok(Response::ClientsVerschoben(ergebnis))
+    }
+
+    /// Notfall-Stummschaltung ueber den Signaling-Dienst
+    ///
+    /// Berechtigung, Ausnahmen und Audit-Eintrag behandelt der
+    /// Signaling-Dienst; der Zustand lebt im Voice-Router.
+    async fn kanal_notfall_stumm(&self, auftrag: NotfallStummAuftrag) -> CommanderResult<Response> {
+        let schalter = self.notfall_stumm.get().ok_or_else(|| {
+            CommanderError::Intern(anyhow::anyhow!("Notfall-Stummschaltung nicht verfuegbar"))
+        })?;
+        let ergebnis = schalter(auftrag).await?;
+        Ok(Response::NotfallStumm(ergebnis))
+    }
+
+    /// Aktive Sprecher eines Kanals (Momentaufnahme fuer Dashboards)
+    fn aktive_sprecher(&self, kanal_id: Uuid) -> CommanderResult<Response> {
+        let abfrage = self.sprecher_abfrage.get().ok_or_else(|| {
+            CommanderError::Intern(anyhow::anyhow!("Sprecher-Abfrage nicht verfuegbar"))
+        })?;
+        Ok(Response::AktiveSprecher(abfrage(kanal_id)))
+    }
+
+    /// Netzwerkwerte und Sprachqualitaet eines Clients mit Voice-Verbindung
+    fn voice_diagnose(&self, client_id: Uuid) -> CommanderResult<Response> {
+        let diagnose = self.voice_diagnose.get().ok_or_else(|| {
+            CommanderError::Intern(anyhow::anyhow!("Voice-Diagnose nicht verfuegbar"))
+        })?;
+        diagnose(client_id)
+            .map(Response::VoiceDiagnose)
+            .ok_or_else(|| {
+                CommanderError::NichtGefunden(format!("Voice-Verbindung von Client {client_id}"))
+            })
+    }
+
     async fn client_poken(
         &self,
         session: &CommanderSession,
@@ -559,24 +1502,272 @@ where
                 "Nachricht zu lang (max. 500 Zeichen)".into(),
             ));
         }
-        // In Produktion: Echtzeit-Benachrichtigung senden
+        self.signaling()?
+            .anstupsen(client_id, nachricht.clone())
+            .await?;
         tracing::info!(
             aktor = %session.benutzer.username,
             client = %client_id,
-            "Client wird angepikt"
+            "Client angepikt"
+        );
+        self.audit(NeuerAuditEintrag::neu(
+            Some(session.benutzer.id),
+            "client.gepikt",
+            Some("user"),
+            Some(&client_id.to_string()),
+            serde_json::json!({ "nachricht": nachricht }),
+        ))
+        .await?;
+        Ok(Response::Ok)
+    }
+
+    // -----------------------------------------------------------------------
+    // Konto-Befehle
+    // -----------------------------------------------------------------------
+
+    /// Erstellt den Datenexport eines Benutzers ueber den Kontodienst
+    ///
+    /// Der Download-Link wird dem Administrator ausgegeben, der ihn an den
+    /// Benutzer weiterreicht.
+    async fn konto_exportieren(
+        &self,
+        session: &CommanderSession,
+        benutzer_id: Uuid,
+    ) -> CommanderResult<Response> {
+        let export = self.konto_export.get().ok_or_else(|| {
+            CommanderError::Intern(anyhow::anyhow!("Datenexport nicht verfuegbar"))
+        })?;
+        let ergebnis = export(KontoAuftrag {
+            aktor_id: session.benutzer.id,
+            benutzer_id,
+        })
+        .await?;
+        self.audit_sicher(NeuerAuditEintrag::neu(
+            Some(session.benutzer.id),
+            "konto.exportiert",
+            Some("user"),
+            Some(&benutzer_id.to_string()),
+            serde_json::json!({ "export_id": ergebnis.export_id }),
+        ))
+        .await?;
+        Ok(Response::KontoExport(ergebnis))
+    }
+
+    /// Loescht ein Konto ueber den Kontodienst
+    ///
+    /// Den Audit-Eintrag (ohne personenbezogene Daten) schreibt die
+    /// Datenbank zusammen mit der Loeschung.
+    async fn konto_loeschen(
+        &self,
+        session: &CommanderSession,
+        benutzer_id: Uuid,
+    ) -> CommanderResult<Response> {
+        if benutzer_id == session.benutzer.id {
+            return Err(CommanderError::UngueltigeEingabe(
+                "Das eigene Konto kann nur im Client geloescht werden".into(),
+            ));
+        }
+        let loeschen = self.konto_loeschen.get().ok_or_else(|| {
+            CommanderError::Intern(anyhow::anyhow!("Kontoloeschung nicht verfuegbar"))
+        })?;
+        self.letzten_administrator_schuetzen(benutzer_id).await?;
+        let ergebnis = loeschen(KontoAuftrag {
+            aktor_id: session.benutzer.id,
+            benutzer_id,
+        })
+        .await?;
+        tracing::info!(
+            aktor = %session.benutzer.username,
+            benutzer = %benutzer_id,
+            "Konto geloescht"
         );
-        self.audit_repo
-            .log_event(
-                Some(session.benutzer.id),
-                "client.gepikt",
-                Some("user"),
-                Some(&client_id.to_string()),
-                serde_json::json!({ "nachricht": nachricht }),
+        Ok(Response::KontoGeloescht(ergebnis))
+    }
+
+    // -----------------------------------------------------------------------
+    // Benutzerverwaltung
+    // -----------------------------------------------------------------------
+
+    /// Listet alle registrierten Benutzer (auch gesperrte), nach Namen sortiert
+    ///
+    /// Der Filter vergleicht ohne Beachtung der Gross-/Kleinschreibung.
+    async fn benutzer_liste(
+        &self,
+        filter: Option<String>,
+        limit: u32,
+        offset: u32,
+    ) -> CommanderResult<Response> {
+        let filter = filter.filter(|f| !f.is_empty()).map(|f| f.to_lowercase());
+        let passende: Vec<BenutzerRecord> = self
+            .user_repo
+            .list(false)
+            .await?
+            .into_iter()
+            .filter(|b| {
+                filter
+                    .as_deref()
+                    .is_none_or(|f| b.username.to_lowercase().contains(f))
+            })
+            .collect();
+        let gesamt = passende.len() as u64;
+        let mut benutzer = Vec::new();
+        for b in passende
+            .into_iter()
+            .skip(offset as usize)
+            .take(limit as usize)
+        {
+            let administrator = self.ist_administrator(b.id).await?;
+            benutzer.push(benutzer_zu_info(b, administrator));
+        }
+        Ok(Response::BenutzerListe(BenutzerSeite {
+            benutzer,
+            gesamt,
+            limit,
+            offset,
+        }))
+    }
+
+    async fn benutzer_passwort_setzen(
+        &self,
+        session: &CommanderSession,
+        user_id: Uuid,
+        neues_passwort: String,
+    ) -> CommanderResult<Response> {
+        if neues_passwort.chars().count() < MIN_PASSWORT_LAENGE {
+            return Err(CommanderError::UngueltigeEingabe(format!(
+                "Passwort muss mindestens {MIN_PASSWORT_LAENGE} Zeichen haben"
+            )));
+        }
+        self.benutzer_laden(user_id).await?;
+        let benutzer = self
+            .auth_service
+            .passwort_setzen(user_id, &neues_passwort)
+            .await?;
+        self.audit_sicher(NeuerAuditEintrag::neu(
+            Some(session.benutzer.id),
+            "benutzer.passwort_gesetzt",
+            Some("user"),
+            Some(&user_id.to_string()),
+            serde_json::json!({}),
+        ))
+        .await?;
+        let administrator = self.ist_administrator(user_id).await?;
+        Ok(Response::Benutzer(benutzer_zu_info(
+            benutzer,
+            administrator,
+        )))
+    }
+
+    /// Sperrt einen Benutzer oder schaltet ihn wieder frei
+    ///
+    /// Sessions und API-Tokens eines gesperrten Benutzers werden bei der
+    /// naechsten Pruefung abgewiesen und gelten nach der Freischaltung wieder.
+    async fn benutzer_deaktivieren(
+        &self,
+        session: &CommanderSession,
+        user_id: Uuid,
+        deaktiviert: bool,
+    ) -> CommanderResult<Response> {
+        self.benutzer_laden(user_id).await?;
+        if deaktiviert {
+            if user_id == session.benutzer.id {
+                return Err(CommanderError::UngueltigeEingabe(
+                    "Das eigene Konto kann nicht gesperrt werden".into(),
+                ));
+            }
+            self.letzten_administrator_schuetzen(user_id).await?;
+        }
+        let benutzer = self
+            .user_repo
+            .update(
+                user_id,
+                BenutzerUpdate {
+                    is_active: Some(!deaktiviert),
+                    ..Default::default()
+                },
             )
             .await?;
+        self.audit_sicher(NeuerAuditEintrag::neu(
+            Some(session.benutzer.id),
+            if deaktiviert {
+                "benutzer.gesperrt"
+            } else {
+                "benutzer.freigeschaltet"
+            },
+            Some("user"),
+            Some(&user_id.to_string()),
+            serde_json::json!({}),
+        ))
+        .await?;
+        let administrator = self.ist_administrator(user_id).await?;
+        Ok(Response::Benutzer(benutzer_zu_info(
+            benutzer,
+            administrator,
+        )))
+    }
+
+    /// Entfernt einen Benutzer: Anmeldung gesperrt, Sessions beendet und
+    /// API-Tokens widerrufen; Nachrichten und Dateien bleiben erhalten
+    async fn benutzer_loeschen(
+        &self,
+        session: &CommanderSession,
+        user_id: Uuid,
+    ) -> CommanderResult<Response> {
+        if user_id == session.benutzer.id {
+            return Err(CommanderError::UngueltigeEingabe(
+                "Das eigene Konto kann nicht entfernt werden".into(),
+            ));
+        }
+        self.benutzer_laden(user_id).await?;
+        self.letzten_administrator_schuetzen(user_id).await?;
+        self.user_repo.delete(user_id).await?;
+        let (sessions, api_tokens) = self.auth_service.zugaenge_beenden(user_id).await;
+        self.audit_sicher(NeuerAuditEintrag::neu(
+            Some(session.benutzer.id),
+            "benutzer.entfernt",
+            Some("user"),
+            Some(&user_id.to_string()),
+            serde_json::json!({ "sessions": sessions, "api_tokens": api_tokens }),
+        ))
+        .await?;
         Ok(Response::Ok)
     }
 
+    async fn benutzer_laden(&self, user_id: Uuid) -> CommanderResult<BenutzerRecord> {
+        self.user_repo
+            .get_by_id(user_id)
+            .await?
+            .ok_or_else(|| CommanderError::NichtGefunden(format!("Benutzer {user_id}")))
+    }
+
+    /// Ob dem Benutzer [`ADMIN_BERECHTIGUNG`] auf Server-Ebene ausdruecklich
+    /// gewaehrt ist (ohne Regel gilt er nicht als Administrator)
+    async fn ist_administrator(&self, user_id: Uuid) -> CommanderResult<bool> {
+        let berechtigungen = self
+            .permission_service
+            .alle_berechtigungen_holen(user_id, Uuid::nil())
+            .await?;
+        Ok(matches!(
+            berechtigungen.get(ADMIN_BERECHTIGUNG),
+            Some(BerechtigungsWert::TriState(TriState::Grant))
+        ))
+    }
+
+    /// Lehnt ab, wenn `user_id` der einzige aktive Administrator ist
+    async fn letzten_administrator_schuetzen(&self, user_id: Uuid) -> CommanderResult<()> {
+        if !self.ist_administrator(user_id).await? {
+            return Ok(());
+        }
+        for anderer in self.user_repo.list(true).await? {
+            if anderer.id != user_id && self.ist_administrator(anderer.id).await? {
+                return Ok(());
+            }
+        }
+        Err(CommanderError::UngueltigeEingabe(
+            "Das letzte Administrator-Konto kann nicht gesperrt oder entfernt werden".into(),
+        ))
+    }
+
     // -----------------------------------------------------------------------
     // Berechtigungs-Befehle
     // -----------------------------------------------------------------------
@@ -597,6 +1788,33 @@ where
         Ok(Response::BerechtigungListe(result))
     }
 
+    async fn berechtigung_effektiv(
+        &self,
+        user_id: Uuid,
+        kanal_id: Option<Uuid>,
+    ) -> CommanderResult<Response> {
+        if self.user_repo.get_by_id(user_id).await?.is_none() {
+            return Err(CommanderError::NichtGefunden(format!(
+                "Benutzer {user_id} nicht gefunden"
+            )));
+        }
+        if let Some(kanal_id) = kanal_id {
+            self.channel_repo
+                .get_by_id(kanal_id)
+                .await?
+                .ok_or_else(|| CommanderError::NichtGefunden(format!("Kanal {kanal_id}")))?;
+        }
+
+        // Ohne Kanal gilt wie im Signaling der Server-Root (Nil-UUID)
+        let spuren = self
+            .permission_service
+            .berechtigungen_nachverfolgen(user_id, kanal_id.unwrap_or_else(Uuid::nil))
+            .await?;
+        Ok(Response::BerechtigungEffektiv(
+            spuren.into_iter().map(spur_zu_eintrag).collect(),
+        ))
+    }
+
     async fn berechtigung_setzen(
         &self,
         session: &CommanderSession,
@@ -610,15 +1828,14 @@ where
         self.permission_repo
             .set_permission(&ziel_parsed, &permission, db_wert, kanal_id)
             .await?;
-        self.audit_repo
-            .log_event(
-                Some(session.benutzer.id),
-                "berechtigung.gesetzt",
-                Some("permission"),
-                Some(&permission),
-                serde_json::json!({ "ziel": ziel, "scope": scope }),
-            )
-            .await?;
+        self.audit_sicher(NeuerAuditEintrag::neu(
+            Some(session.benutzer.id),
+            "berechtigung.gesetzt",
+            Some("permission"),
+            Some(&permission),
+            serde_json::json!({ "ziel": ziel, "scope": scope }),
+        ))
+        .await?;
         Ok(Response::Ok)
     }
 
@@ -633,25 +1850,45 @@ where
         self.permission_repo
             .remove_permission(&ziel_parsed, &permission, kanal_id)
             .await?;
-        self.audit_repo
-            .log_event(
-                Some(session.benutzer.id),
-                "berechtigung.entfernt",
-                Some("permission"),
-                Some(&permission),
-                serde_json::json!({ "ziel": ziel, "scope": scope }),
-            )
-            .await?;
+        self.audit_sicher(NeuerAuditEintrag::neu(
+            Some(session.benutzer.id),
+            "berechtigung.entfernt",
+            Some("permission"),
+            Some(&permission),
+            serde_json::json!({ "ziel": ziel, "scope": scope }),
+        ))
+        .await?;
         Ok(Response::Ok)
     }
 
     // -----------------------------------------------------------------------
-    // Datei-Befehle (Stub)
+    // Datei-Befehle
     // -----------------------------------------------------------------------
 
-    async fn datei_liste(&self, _kanal_id: Uuid) -> CommanderResult<Response> {
-        // In Produktion: Datei-Repository abfragen
-        Ok(Response::DateiListe(vec![]))
+    async fn datei_liste(&self, kanal_id: Uuid) -> CommanderResult<Response> {
+        let dateien = self.file_repo.list_by_channel(kanal_id).await?;
+        let ids: Vec<Uuid> = dateien.iter().map(|d| d.id).collect();
+        let zugriffe: HashMap<Uuid, i64> = self
+            .file_repo
+            .count_access_by_file(&ids)
+            .await?
+            .into_iter()
+            .collect();
+        let mut eintraege = Vec::with_capacity(dateien.len());
+        for d in dateien {
+            let zugriffe = zugriffe.get(&d.id).copied().unwrap_or(0);
+            eintraege.push(DateiEintrag {
+                datei_id: d.id.to_string(),
+                name: d.filename,
+                groesse_bytes: d.size_bytes.max(0) as u64,
+                kanal_id: d.channel_id,
+                hochgeladen_von: d.uploader_id,
+                hochgeladen_am_ms: d.created_at.timestamp_millis().max(0) as u64,
+                mime_typ: d.mime_type,
+                zugriffe: zugriffe.max(0) as u64,
+            });
+        }
+        Ok(Response::DateiListe(eintraege))
     }
 
     async fn datei_loeschen(
@@ -665,15 +1902,211 @@ where
             datei = %datei_id,
             "Datei wird geloescht"
         );
-        self.audit_repo
-            .log_event(
-                Some(session.benutzer.id),
-                "datei.geloescht",
-                Some("file"),
-                Some(&datei_id),
-                serde_json::json!({}),
-            )
-            .await?;
+        self.audit(NeuerAuditEintrag::neu(
+            Some(session.benutzer.id),
+            "datei.geloescht",
+            Some("file"),
+            Some(&datei_id),
+            serde_json::json!({}),
+        ))
+        .await?;
+        Ok(Response::Ok)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    async fn datei_zugriffe(
+        &self,
+        session: &CommanderSession,
+        datei_id: Option<Uuid>,
+        benutzer_id: Option<Uuid>,
+        von: Option<chrono::DateTime<Utc>>,
+        bis: Option<chrono::DateTime<Utc>>,
+        limit: u32,
+        offset: u32,
+    ) -> CommanderResult<Response> {
+        if datei_id.is_none() && benutzer_id.is_none() {
+            return Err(CommanderError::UngueltigeEingabe(
+                "datei_id oder benutzer_id erforderlich".into(),
+            ));
+        }
+        if let (Some(von), Some(bis)) = (von, bis) {
+            if von > bis {
+                return Err(CommanderError::UngueltigeEingabe(
+                    "Zeitraum ungueltig: von liegt nach bis".into(),
+                ));
+            }
+        }
+
+        let filter = DateiZugriffFilter {
+            file_id: datei_id,
+            user_id: benutzer_id,
+            since: von,
+            until: bis,
+            limit: Some(limit as i64),
+            offset: Some(offset as i64),
+        };
+        let gesamt = self.file_repo.count_access(filter.clone()).await?;
+        let zugriffe = self.file_repo.list_access(filter).await?;
+
+        // Abfragen des Zugriffsprotokolls sind selbst auditpflichtig
+        self.audit(NeuerAuditEintrag::neu(
+            Some(session.benutzer.id),
+            "datei.zugriffe_abgefragt",
+            datei_id.map(|_| "file").or(benutzer_id.map(|_| "user")),
+            datei_id.or(benutzer_id).map(|id| id.to_string()).as_deref(),
+            serde_json::json!({ "limit": limit, "offset": offset }),
+        ))
+        .await?;
+
+        let eintraege: Vec<DateiZugriffEintrag> = zugriffe
+            .into_iter()
+            .map(|z| DateiZugriffEintrag {
+                id: z.id,
+                datei_id: z.file_id,
+                benutzer_id: z.user_id,
+                zeitstempel: z.timestamp,
+                bytes_ausgeliefert: z.bytes_served.max(0) as u64,
+                vollstaendig: z.completed,
+            })
+            .collect();
+
+        Ok(Response::DateiZugriffe(DateiZugriffSeite {
+            eintraege,
+            gesamt: gesamt.max(0) as u64,
+            limit,
+            offset,
+        }))
+    }
+
+    // -----------------------------------------------------------------------
+    // Chat-Befehle
+    // -----------------------------------------------------------------------
+
+    async fn nachricht_info(
+        &self,
+        session: &CommanderSession,
+        nachricht_id: Uuid,
+    ) -> CommanderResult<Response> {
+        let nachricht = ChatMessageRepository::get_by_id(self.file_repo.as_ref(), nachricht_id)
+            .await?
+            .ok_or_else(|| CommanderError::NichtGefunden(format!("Nachricht {nachricht_id}")))?;
+
+        // Einsicht in fremde Nachrichten ist selbst auditpflichtig
+        self.audit(NeuerAuditEintrag::neu(
+            Some(session.benutzer.id),
+            "chat.nachricht_abgefragt",
+            Some("message"),
+            Some(&nachricht_id.to_string()),
+            serde_json::json!({ "kanal_id": nachricht.channel_id }),
+        ))
+        .await?;
+
+        Ok(Response::Nachricht(NachrichtInfo {
+            id: nachricht.id,
+            kanal_id: nachricht.channel_id,
+            sender_id: nachricht.sender_id,
+            inhalt: nachricht.content,
+            erstellt_am: nachricht.created_at,
+            bearbeitet_am: nachricht.edited_at,
+            geloescht_am: nachricht.deleted_at,
+            bearbeitungen: nachricht.edit_count,
+        }))
+    }
+
+    // -----------------------------------------------------------------------
+    // Soundboard-Befehle
+    // -----------------------------------------------------------------------
+
+    async fn soundboard_liste(&self, kanal_id: Option<Uuid>) -> CommanderResult<Response> {
+        let sounds = SoundboardRepository::list(self.file_repo.as_ref(), kanal_id).await?;
+        Ok(Response::SoundboardListe(
+            sounds.into_iter().map(sound_zu_info).collect(),
+        ))
+    }
+
+    /// Kodiert eine hochgeladene Datei einmalig und legt sie als Sound ab
+    ///
+    /// Die Hoechstdauer wird hier durchgesetzt; beim Abspielen taktet der
+    /// Server nur noch die fertigen Frames aus.
+    async fn soundboard_registrieren(
+        &self,
+        session: &CommanderSession,
+        name: String,
+        datei_id: Uuid,
+        kanal_id: Option<Uuid>,
+    ) -> CommanderResult<Response> {
+        let name = name.trim();
+        if name.is_empty() || name.chars().count() > SOUND_NAME_MAX {
+            return Err(CommanderError::UngueltigeEingabe(format!(
+                "Sound-Name muss 1 bis {SOUND_NAME_MAX} Zeichen lang sein"
+            )));
+        }
+        if let Some(kanal_id) = kanal_id {
+            if self.channel_repo.get_by_id(kanal_id).await?.is_none() {
+                return Err(CommanderError::NichtGefunden(format!(
+                    "Kanal {kanal_id} nicht gefunden"
+                )));
+            }
+        }
+        let datei = FileRepository::get_by_id(self.file_repo.as_ref(), datei_id)
+            .await?
+            .filter(|d| d.deleted_at.is_none())
+            .ok_or_else(|| {
+                CommanderError::NichtGefunden(format!("Datei {datei_id} nicht gefunden"))
+            })?;
+
+        let kodierer = self.soundboard_kodierer.get().ok_or_else(|| {
+            CommanderError::Intern(anyhow::anyhow!("Soundboard-Kodierer nicht verfuegbar"))
+        })?;
+        let kodiert = kodierer(datei.storage_path, SOUNDBOARD_MAX_DAUER).await?;
+
+        let sound = SoundboardRepository::create(
+            self.file_repo.as_ref(),
+            NeuerSound {
+                name,
+                channel_id: kanal_id,
+                file_id: Some(datei_id),
+                frame_ms: kodiert.frame_ms,
+                frames: &kodiert.frames,
+                created_by: Some(session.benutzer.id),
+            },
+        )
+        .await
+        .map_err(eingabe_fehler)?;
+        self.audit(NeuerAuditEintrag::neu(
+            Some(session.benutzer.id),
+            "soundboard.registriert",
+            Some("sound"),
+            Some(&sound.id.to_string()),
+            serde_json::json!({
+                "name": sound.name,
+                "kanal_id": sound.channel_id,
+                "datei_id": datei_id,
+                "dauer_ms": sound.duration_ms,
+            }),
+        ))
+        .await?;
+        Ok(Response::Sound(sound_zu_info(sound)))
+    }
+
+    async fn soundboard_entfernen(
+        &self,
+        session: &CommanderSession,
+        id: Uuid,
+    ) -> CommanderResult<Response> {
+        if !SoundboardRepository::delete(self.file_repo.as_ref(), id).await? {
+            return Err(CommanderError::NichtGefunden(format!(
+                "Sound {id} nicht gefunden"
+            )));
+        }
+        self.audit(NeuerAuditEintrag::neu(
+            Some(session.benutzer.id),
+            "soundboard.entfernt",
+            Some("sound"),
+            Some(&id.to_string()),
+            serde_json::json!({}),
+        ))
+        .await?;
         Ok(Response::Ok)
     }
 
@@ -708,14 +2141,154 @@ where
             .collect();
         Ok(Response::LogEintraege(eintraege))
     }
-}
 
-// ---------------------------------------------------------------------------
-// Hilfsfunktionen
-// ---------------------------------------------------------------------------
+    // -----------------------------------------------------------------------
+    // Zeitplaner
+    // -----------------------------------------------------------------------
 
-/// Parst ein Ziel-String ("user:<uuid>", "server_group:<uuid>", "server_default")
-/// und einen Scope-String ("server" oder "channel:<uuid>") in DB-Typen.
+    async fn zeitplan_liste(&self) -> CommanderResult<Response> {
+        let aktionen = self.zeitplan_repo.list().await?;
+        Ok(Response::ZeitplanListe(
+            aktionen.into_iter().map(zeitplan_zu_info).collect(),
+        ))
+    }
+
+    async fn zeitplan_erstellen(
+        &self,
+        session: &CommanderSession,
+        name: String,
+        aktion: GeplanteAktion,
+        einmalig: Option<chrono::DateTime<Utc>>,
+        cron: Option<String>,
+        zeitzone: Option<String>,
+    ) -> CommanderResult<Response> {
+        let zeitplan = match (einmalig, cron) {
+            (Some(_), Some(_)) | (None, None) => {
+                return Err(CommanderError::UngueltigeEingabe(
+                    "Genau eines von 'einmalig' und 'cron' angeben".into(),
+                ))
+            }
+            (Some(_), None) if zeitzone.is_some() => {
+                return Err(CommanderError::UngueltigeEingabe(
+                    "'zeitzone' gilt nur fuer wiederkehrende Aktionen".into(),
+                ))
+            }
+            (Some(zeitpunkt), None) => Zeitplan::Einmalig(zeitpunkt),
+            (None, Some(cron)) => {
+                Zeitplan::wiederkehrend(&cron, zeitzone.as_deref().unwrap_or("UTC"))
+                    .map_err(eingabe_fehler)?
+            }
+        };
+        let naechste = zeitplan
+            .erste_ausfuehrung(Utc::now())
+            .map_err(eingabe_fehler)?;
+        self.geplante_aktion_pruefen(&aktion).await?;
+        // Der Zeitplaner fuehrt spaeter mit genau diesen Rechten aus; fehlt
+        // schon jetzt der Scope, wuerde jede Ausfuehrung scheitern
+        for cmd in Command::aus_geplanter_aktion(&aktion) {
+            self.scope_pruefen(&cmd, session)?;
+        }
+
+        let scopes = match session.auth_art {
+            AuthArt::ApiToken | AuthArt::Zertifikat => Some(session.scopes.as_slice()),
+            AuthArt::Session => None,
+        };
+        let record = self
+            .zeitplan_repo
+            .create(NeueGeplanteAktion {
+                name: &name,
+                aktion,
+                zeitplan,
+                next_run_at: naechste,
+                created_by: session.benutzer.id,
+                scopes,
+            })
+            .await
+            .map_err(eingabe_fehler)?;
+
+        self.audit(NeuerAuditEintrag::neu(
+            Some(session.benutzer.id),
+            "zeitplan.erstellt",
+            Some("schedule"),
+            Some(&record.id.to_string()),
+            serde_json::json!({
+                "name": record.name,
+                "typ": record.aktion.typ(),
+                "naechste_ausfuehrung": naechste,
+            }),
+        ))
+        .await?;
+        Ok(Response::Zeitplan(zeitplan_zu_info(record)))
+    }
+
+    /// Prueft, ob die Ziele einer geplanten Aktion (noch) existieren
+    async fn geplante_aktion_pruefen(&self, aktion: &GeplanteAktion) -> CommanderResult<()> {
+        let kanal_ids: Vec<Uuid> = match aktion {
+            GeplanteAktion::KanalAusVorlage {
+                vorlage_id,
+                parent_id,
+                loeschen_nach_min,
+                ..
+            } => {
+                if *loeschen_nach_min == Some(0) {
+                    return Err(CommanderError::UngueltigeEingabe(
+                        "loeschen_nach_min muss groesser als 0 sein".into(),
+                    ));
+                }
+                if self.template_repo.get_by_id(*vorlage_id).await?.is_none() {
+                    return Err(CommanderError::NichtGefunden(format!(
+                        "Kanal-Vorlage {vorlage_id} nicht gefunden"
+                    )));
+                }
+                parent_id.iter().copied().collect()
+            }
+            GeplanteAktion::KanaeleLoeschen { kanal_ids } => {
+                if kanal_ids.is_empty() {
+                    return Err(CommanderError::UngueltigeEingabe(
+                        "Mindestens ein Kanal zum Loeschen erforderlich".into(),
+                    ));
+                }
+                kanal_ids.clone()
+            }
+        };
+        for id in kanal_ids {
+            if self.channel_repo.get_by_id(id).await?.is_none() {
+                return Err(CommanderError::NichtGefunden(format!(
+                    "Kanal {id} nicht gefunden"
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    async fn zeitplan_abbrechen(
+        &self,
+        session: &CommanderSession,
+        id: Uuid,
+    ) -> CommanderResult<Response> {
+        if !self.zeitplan_repo.cancel(id).await? {
+            return Err(CommanderError::NichtGefunden(format!(
+                "Aktive geplante Aktion {id} nicht gefunden"
+            )));
+        }
+        self.audit(NeuerAuditEintrag::neu(
+            Some(session.benutzer.id),
+            "zeitplan.abgebrochen",
+            Some("schedule"),
+            Some(&id.to_string()),
+            serde_json::json!({}),
+        ))
+        .await?;
+        Ok(Response::Ok)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Hilfsfunktionen
+// ---------------------------------------------------------------------------
+
+/// Parst ein Ziel-String ("user:<uuid>", "server_group:<uuid>", "server_default")
+/// und einen Scope-String ("server" oder "channel:<uuid>") in DB-Typen.
 fn ziel_parsen(ziel: &str, scope: &str) -> CommanderResult<(BerechtigungsZiel, Option<Uuid>)> {
     let ziel_parsed = if ziel == "server_default" {
         BerechtigungsZiel::ServerDefault
@@ -752,6 +2325,119 @@ fn ziel_parsen(ziel: &str, scope: &str) -> CommanderResult<(BerechtigungsZiel, O
     Ok((ziel_parsed, kanal_id))
 }
 
+/// Filtert die verbundenen Clients und schneidet die angeforderte Seite aus
+///
+/// Der Namensfilter vergleicht ohne Beachtung der Gross-/Kleinschreibung.
+fn client_seite(
+    clients: Vec<ClientInfo>,
+    kanal_id: Option<Uuid>,
+    username: Option<&str>,
+    seite: SeitenAnfrage,
+) -> ClientSeite {
+    let username = username.filter(|n| !n.is_empty()).map(str::to_lowercase);
+    let passende: Vec<ClientInfo> = clients
+        .into_iter()
+        .filter(|c| kanal_id.is_none_or(|k| c.kanal_id == Some(k)))
+        .filter(|c| {
+            username
+                .as_deref()
+                .is_none_or(|n| c.username.to_lowercase().contains(n))
+        })
+        .collect();
+    let gesamt = passende.len() as u64;
+    ClientSeite {
+        clients: passende
+            .into_iter()
+            .skip(usize::try_from(seite.offset()).unwrap_or(usize::MAX))
+            .take(seite.groesse as usize)
+            .collect(),
+        seite: seite.info(gesamt),
+    }
+}
+
+fn benutzer_zu_info(b: BenutzerRecord, administrator: bool) -> BenutzerInfo {
+    BenutzerInfo {
+        id: b.id,
+        username: b.username,
+        erstellt_am: b.created_at,
+        letzter_login: b.last_login,
+        aktiv: b.is_active,
+        passwort_geaendert: b.password_changed,
+        administrator,
+    }
+}
+
+fn kanal_zu_info(k: KanalRecord) -> KanalInfo {
+    KanalInfo {
+        id: k.id,
+        name: k.name,
+        parent_id: k.parent_id,
+        thema: k.topic,
+        max_clients: k.max_clients,
+        aktuelle_clients: 0,
+        sort_order: k.sort_order,
+        passwort_geschuetzt: k.password_hash.is_some(),
+        langsammodus_sek: k.slow_mode_secs,
+        bearbeitungsfrist_sek: k.edit_window_secs,
+    }
+}
+
+fn vorlage_zu_info(v: KanalVorlageRecord) -> VorlageInfo {
+    VorlageInfo {
+        id: v.id,
+        anzahl_kanaele: v.root.anzahl_kanaele(),
+        tiefe: v.root.tiefe(),
+        definition: serde_json::to_value(&v.root).unwrap_or_default(),
+        name: v.name,
+        beschreibung: v.description,
+        eingebaut: v.builtin,
+    }
+}
+
+fn sound_zu_info(s: SoundRecord) -> SoundInfo {
+    SoundInfo {
+        id: s.id,
+        name: s.name,
+        kanal_id: s.channel_id,
+        datei_id: s.file_id,
+        dauer_ms: s.duration_ms,
+        erstellt_von: s.created_by,
+        erstellt_am: s.created_at,
+    }
+}
+
+fn zeitplan_zu_info(a: GeplanteAktionRecord) -> ZeitplanInfo {
+    let zeitzone = a.zeitplan.zeitzone().name().to_string();
+    let (einmalig, cron) = match a.zeitplan {
+        Zeitplan::Einmalig(zeitpunkt) => (Some(zeitpunkt), None),
+        Zeitplan::Wiederkehrend { cron, .. } => (None, Some(cron.to_string())),
+    };
+    ZeitplanInfo {
+        id: a.id,
+        name: a.name,
+        aktion: a.aktion,
+        einmalig,
+        cron,
+        zeitzone,
+        naechste_ausfuehrung: a.next_run_at,
+        letzte_ausfuehrung: a.last_run_at,
+        erstellt_von: a.created_by,
+        abgebrochen: a.cancelled,
+        erstellt_am: a.created_at,
+    }
+}
+
+/// Validierungsfehler (Vorlagen, Einstellungen) sind Eingabefehler, keine Serverfehler
+fn eingabe_fehler(e: DbError) -> CommanderError {
+    match e {
+        DbError::UngueltigeDaten(msg) | DbError::Eindeutigkeit(msg) => {
+            CommanderError::UngueltigeEingabe(msg)
+        }
+        DbError::NichtGefunden(msg) => CommanderError::NichtGefunden(msg),
+        andere => CommanderError::Datenbank(andere),
+    }
+}
+
 fn db_wert_zu_input(wert: BerechtigungsWert) -> BerechtigungsWertInput {
     match wert {
         BerechtigungsWert::TriState(speakeasy_db::models::TriState::Grant) => {
@@ -768,6 +2454,25 @@ fn db_wert_zu_input(wert: BerechtigungsWert) -> BerechtigungsWertInput {
     }
 }
 
+fn spur_regel(eintrag: SpurEintrag) -> BerechtigungsRegel {
+    BerechtigungsRegel {
+        quelle: eintrag.stufe.to_string(),
+        geltungsbereich: eintrag.stufe.geltungsbereich().to_string(),
+        wert: db_wert_zu_input(eintrag.wert),
+    }
+}
+
+fn spur_zu_eintrag(spur: BerechtigungsSpur) -> EffektiverBerechtigungsEintrag {
+    let entscheidend = spur.entscheidend.map(spur_regel);
+    EffektiverBerechtigungsEintrag {
+        permission: spur.permission_key,
+        wert: entscheidend.as_ref().map(|r| r.wert.clone()),
+        quelle: entscheidend.as_ref().map(|r| r.quelle.clone()),
+        geltungsbereich: entscheidend.map(|r| r.geltungsbereich),
+        regeln: spur.regeln.into_iter().map(spur_regel).collect(),
+    }
+}
+
 fn input_zu_db_wert(wert: BerechtigungsWertInput) -> BerechtigungsWert {
     match wert {
         BerechtigungsWertInput::Grant => BerechtigungsWert::TriState(TriState::Grant),
@@ -819,6 +2524,48 @@ mod tests {
         assert!(ergebnis.is_err());
     }
 
+    #[test]
+    fn validierungsfehler_sind_eingabefehler() {
+        let e = eingabe_fehler(DbError::UngueltigeDaten("Limit".into()));
+        assert!(matches!(e, CommanderError::UngueltigeEingabe(_)));
+        let e = eingabe_fehler(DbError::nicht_gefunden("Vorlage"));
+        assert!(matches!(e, CommanderError::NichtGefunden(_)));
+    }
+
+    #[test]
+    fn spur_nennt_entscheidende_regel() {
+        use speakeasy_db::permissions::BerechtigungsStufe;
+
+        let spur = BerechtigungsSpur {
+            permission_key: "b_client_poke".into(),
+            entscheidend: Some(SpurEintrag {
+                stufe: BerechtigungsStufe::ServerGruppe {
+                    name: "Moderator".into(),
+                },
+                wert: BerechtigungsWert::TriState(TriState::Grant),
+            }),
+            regeln: vec![
+                SpurEintrag {
+                    stufe: BerechtigungsStufe::Individual,
+                    wert: BerechtigungsWert::TriState(TriState::Skip),
+                },
+                SpurEintrag {
+                    stufe: BerechtigungsStufe::ServerGruppe {
+                        name: "Moderator".into(),
+                    },
+                    wert: BerechtigungsWert::TriState(TriState::Grant),
+                },
+            ],
+        };
+
+        let eintrag = spur_zu_eintrag(spur);
+        assert_eq!(eintrag.wert, Some(BerechtigungsWertInput::Grant));
+        assert_eq!(eintrag.quelle.as_deref(), Some("ServerGruppe(Moderator)"));
+        assert_eq!(eintrag.geltungsbereich.as_deref(), Some("server"));
+        assert_eq!(eintrag.regeln.len(), 2);
+        assert_eq!(eintrag.regeln[0].geltungsbereich, "kanal");
+    }
+
     #[test]
     fn db_wert_konvertierung_grant() {
         let input = BerechtigungsWertInput::Grant;
@@ -827,6 +2574,1015 @@ mod tests {
         assert_eq!(zurueck, input);
     }
 
+    /// Merkt sich, ueber welchen Weg Ereignisse ankommen
+    #[derive(Default)]
+    struct AufzeichnenderSink {
+        gepuffert: std::sync::Mutex<Vec<String>>,
+        sicher: std::sync::Mutex<Vec<String>>,
+    }
+
+    impl AuditSink for AufzeichnenderSink {
+        fn protokollieren(&self, eintrag: NeuerAuditEintrag) {
+            self.gepuffert.lock().unwrap().push(eintrag.action);
+        }
+
+        fn sicher_protokollieren(
+            &self,
+            eintrag: NeuerAuditEintrag,
+        ) -> speakeasy_db::audit_puffer::SinkFuture<'_> {
+            self.sicher.lock().unwrap().push(eintrag.action);
+            Box::pin(async { Ok(()) })
+        }
+    }
+
+    type TestExecutor = CommandExecutor<
+        speakeasy_db::SqliteDb,
+        speakeasy_db::SqliteDb,
+        speakeasy_db::SqliteDb,
+        speakeasy_db::SqliteDb,
+        speakeasy_db::SqliteDb,
+        speakeasy_db::SqliteDb,
+        speakeasy_db::SqliteDb,
+        speakeasy_db::SqliteDb,
+        speakeasy_db::SqliteDb,
+    >;
+
+    fn test_executor(db: &Arc<speakeasy_db::SqliteDb>) -> Arc<TestExecutor> {
+        use speakeasy_auth::{ApiTokenStore, SessionStore};
+
+        CommandExecutor::neu(
+            Arc::clone(db),
+            Arc::clone(db),
+            Arc::clone(db),
+            Arc::clone(db),
+            Arc::clone(db),
+            Arc::clone(db),
+            Arc::clone(db),
+            Arc::clone(db),
+            Arc::clone(db),
+            Arc::new(AuthService::neu(
+                Arc::clone(db),
+                SessionStore::neu(),
+                ApiTokenStore::neu(),
+            )),
+            PermissionService::neu(Arc::clone(db)),
+            BanService::neu(Arc::clone(db)),
+            ServerEinstellungen {
+                name: "Test".into(),
+                willkommensnachricht: None,
+                max_clients: 32,
+                host_nachricht: None,
+                chat_bearbeitungsfrist_sek: 0,
+            },
+            "0.0.0".into(),
+            KanalbaumGrenzen::default(),
+        )
+    }
+
+    async fn admin_session(db: &speakeasy_db::SqliteDb) -> CommanderSession {
+        let admin = UserRepository::create(
+            db,
+            speakeasy_db::models::NeuerBenutzer {
+                username: "admin",
+                password_hash: "hash",
+            },
+        )
+        .await
+        .unwrap();
+        CommanderSession {
+            benutzer: admin,
+            scopes: vec![],
+            auth_art: AuthArt::Session,
+        }
+    }
+
+    #[tokio::test]
+    async fn bans_werden_sicher_protokolliert() {
+        use speakeasy_db::{models::NeuerBenutzer, SqliteDb};
+
+        let db = Arc::new(SqliteDb::in_memory().await.unwrap());
+        let executor = test_executor(&db);
+        let sink = Arc::new(AufzeichnenderSink::default());
+        executor.audit_sink_setzen(Arc::clone(&sink) as Arc<dyn AuditSink>);
+
+        let session = admin_session(&db).await;
+        let stoerer = UserRepository::create(
+            db.as_ref(),
+            NeuerBenutzer {
+                username: "stoerer",
+                password_hash: "hash",
+            },
+        )
+        .await
+        .unwrap();
+
+        executor
+            .ausfuehren(
+                Command::KanalErstellen {
+                    name: "Lobby".into(),
+                    parent_id: None,
+                    thema: None,
+                    passwort: None,
+                    max_clients: 0,
+                    sort_order: 0,
+                    permanent: true,
+                },
+                &session,
+            )
+            .await
+            .unwrap();
+        executor
+            .ausfuehren(
+                Command::ClientBannen {
+                    client_id: stoerer.id,
+                    dauer_secs: None,
+                    grund: None,
+                    ip_bannen: false,
+                    inhalte_entfernen: None,
+                },
+                &session,
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(*sink.gepuffert.lock().unwrap(), vec!["kanal.erstellt"]);
+        assert_eq!(*sink.sicher.lock().unwrap(), vec!["client.gebannt"]);
+    }
+
+    /// Anbindung an einen echten Signaling-Zustand, wie sie der Server setzt
+    struct PruefstandSignaling(speakeasy_signaling::wiedergabe::Pruefstand);
+
+    impl SignalingNotifier for PruefstandSignaling {
+        fn kicken(
+            &self,
+            user_id: Uuid,
+            grund: Option<String>,
+        ) -> BoxFuture<'_, CommanderResult<()>> {
+            Box::pin(async move {
+                speakeasy_signaling::moderation::kicken(
+                    &self.0,
+                    speakeasy_core::types::UserId(user_id),
+                    grund.as_deref(),
+                )
+                .map_err(|e| CommanderError::NichtGefunden(e.to_string()))
+            })
+        }
+
+        fn verschieben(
+            &self,
+            _user_id: Uuid,
+            _kanal_id: Uuid,
+        ) -> BoxFuture<'_, CommanderResult<()>> {
+            Box::pin(async { Ok(()) })
+        }
+
+        fn anstupsen(
+            &self,
+            _user_id: Uuid,
+            _nachricht: String,
+        ) -> BoxFuture<'_, CommanderResult<()>> {
+            Box::pin(async { Ok(()) })
+        }
+
+        fn kanal_geaendert(&self, _kanal_id: Uuid) -> BoxFuture<'_, CommanderResult<()>> {
+            Box::pin(async { Ok(()) })
+        }
+
+        fn ip_adresse(&self, user_id: Uuid) -> CommanderResult<String> {
+            self.0
+                .presence
+                .client_presence(&speakeasy_core::types::UserId(user_id))
+                .and_then(|p| p.ip)
+                .ok_or_else(|| CommanderError::NichtGefunden(user_id.to_string()))
+        }
+    }
+
+    #[tokio::test]
+    async fn ban_trennt_verbundenen_client_samt_voice() {
+        use speakeasy_core::types::UserId;
+        use speakeasy_db::BanRepository;
+        use speakeasy_signaling::presence::ClientPresence;
+
+        let db = Arc::new(speakeasy_db::SqliteDb::in_memory().await.unwrap());
+        let executor = test_executor(&db);
+        let session = admin_session(&db).await;
+        let signaling = speakeasy_signaling::wiedergabe::pruefstand().await.unwrap();
+        executor.signaling_setzen(Arc::new(PruefstandSignaling(Arc::clone(&signaling))));
+
+        let stoerer = UserId(
+            UserRepository::create(
+                db.as_ref(),
+                speakeasy_db::models::NeuerBenutzer {
+                    username: "stoerer",
+                    password_hash: "hash",
+                },
+            )
+            .await
+            .unwrap()
+            .id,
+        );
+        signaling.presence.client_verbunden(ClientPresence {
+            user_id: stoerer,
+            username: "stoerer".into(),
+            display_name: "Stoerer".into(),
+            channel_id: None,
+            is_input_muted: false,
+            is_output_muted: false,
+            is_away: false,
+            away_message: None,
+            nur_hoeren: false,
+            nur_hoeren_gewuenscht: false,
+            ip: Some("203.0.113.7".into()),
+        });
+        signaling.voice_state.client_registrieren(
+            stoerer,
+            4711,
+            "203.0.113.7:40000".parse().unwrap(),
+        );
+
+        executor
+            .ausfuehren(
+                Command::ClientBannen {
+                    client_id: stoerer.inner(),
+                    dauer_secs: None,
+                    grund: Some("Spam".into()),
+                    ip_bannen: true,
+                    inhalte_entfernen: None,
+                },
+                &session,
+            )
+            .await
+            .unwrap();
+
+        assert!(signaling.voice_state.ssrc_von_user(&stoerer).is_none());
+        assert!(signaling.voice_state.user_id_von_ssrc(4711).is_none());
+        assert!(!signaling.presence.ist_online(&stoerer));
+        let ban = BanRepository::is_banned(db.as_ref(), None, Some("203.0.113.7"))
+            .await
+            .unwrap()
+            .expect("IP-Ban fehlt");
+        assert_eq!(ban.user_id, Some(stoerer.inner()));
+
+        // Offline laesst sich keine IP mehr ermitteln
+        let erneut = executor
+            .ausfuehren(
+                Command::ClientBannen {
+                    client_id: stoerer.inner(),
+                    dauer_secs: None,
+                    grund: None,
+                    ip_bannen: true,
+                    inhalte_entfernen: None,
+                },
+                &session,
+            )
+            .await;
+        assert!(matches!(erneut, Err(CommanderError::NichtGefunden(_))));
+    }
+
+    #[tokio::test]
+    async fn server_historie_und_unsauberer_neustart() {
+        use speakeasy_db::{models::NeuerServerStart, ServerStartRepository};
+
+        let db = Arc::new(speakeasy_db::SqliteDb::in_memory().await.unwrap());
+        let executor = test_executor(&db);
+        let session = admin_session(&db).await;
+        let jetzt = chrono::Utc::now();
+        for (i, sauber) in [true, false].into_iter().enumerate() {
+            ServerStartRepository::create(
+                db.as_ref(),
+                NeuerServerStart {
+                    started_at: jetzt + chrono::Duration::seconds(i as i64),
+                    version: "0.0.0",
+                    clean_shutdown: sauber,
+                    config_hash: "abc",
+                },
+            )
+            .await
+            .unwrap();
+        }
+        let letzter = ServerStartRepository::list_recent(db.as_ref(), 1)
+            .await
+            .unwrap()
+            .remove(0);
+        executor.neustart_setzen(letzter);
+
+        match executor
+            .ausfuehren(Command::ServerHistorie { limit: 10 }, &session)
+            .await
+            .unwrap()
+        {
+            Response::ServerHistorie(starts) => {
+                let flags: Vec<_> = starts.iter().map(|s| s.sauber_beendet).collect();
+                assert_eq!(flags, [false, true]);
+            }
+            andere => panic!("unerwartet: {andere:?}"),
+        }
+        match executor
+            .ausfuehren(Command::ServerInfo, &session)
+            .await
+            .unwrap()
+        {
+            Response::ServerInfo(info) => assert!(info.letzter_shutdown_unsauber),
+            andere => panic!("unerwartet: {andere:?}"),
+        }
+    }
+
+    /// Signaling-Anbindung mit fester Menge verbundener Clients
+    #[derive(Default)]
+    struct TestSignaling {
+        verbunden: std::sync::Mutex<Vec<Uuid>>,
+        gekickt: std::sync::Mutex<Vec<(Uuid, Option<String>)>>,
+        geaenderte_kanaele: std::sync::Mutex<Vec<Uuid>>,
+    }
+
+    impl TestSignaling {
+        fn online(&self, user_id: Uuid) -> CommanderResult<()> {
+            if self.verbunden.lock().unwrap().contains(&user_id) {
+                Ok(())
+            } else {
+                Err(CommanderError::NichtGefunden(format!(
+                    "Client {user_id} nicht verbunden"
+                )))
+            }
+        }
+    }
+
+    impl SignalingNotifier for TestSignaling {
+        fn kicken(
+            &self,
+            user_id: Uuid,
+            grund: Option<String>,
+        ) -> BoxFuture<'_, CommanderResult<()>> {
+            Box::pin(async move {
+                self.online(user_id)?;
+                self.verbunden.lock().unwrap().retain(|id| *id != user_id);
+                self.gekickt.lock().unwrap().push((user_id, grund));
+                Ok(())
+            })
+        }
+
+        fn verschieben(
+            &self,
+            user_id: Uuid,
+            _kanal_id: Uuid,
+        ) -> BoxFuture<'_, CommanderResult<()>> {
+            Box::pin(async move { self.online(user_id) })
+        }
+
+        fn anstupsen(
+            &self,
+            user_id: Uuid,
+            _nachricht: String,
+        ) -> BoxFuture<'_, CommanderResult<()>> {
+            Box::pin(async move { self.online(user_id) })
+        }
+
+        fn kanal_geaendert(&self, kanal_id: Uuid) -> BoxFuture<'_, CommanderResult<()>> {
+            Box::pin(async move {
+                self.geaenderte_kanaele.lock().unwrap().push(kanal_id);
+                Ok(())
+            })
+        }
+
+        fn ip_adresse(&self, user_id: Uuid) -> CommanderResult<String> {
+            self.online(user_id).map(|()| "127.0.0.1".into())
+        }
+    }
+
+    #[tokio::test]
+    async fn langsam_modus_wird_gespeichert_und_verteilt() {
+        let db = Arc::new(speakeasy_db::SqliteDb::in_memory().await.unwrap());
+        let executor = test_executor(&db);
+        let session = admin_session(&db).await;
+        let kanal = ChannelRepository::create(
+            db.as_ref(),
+            NeuerKanal {
+                name: "Plaudern",
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        let signaling = Arc::new(TestSignaling::default());
+        executor.signaling_setzen(Arc::clone(&signaling) as Arc<dyn SignalingNotifier>);
+        let bearbeiten = |langsammodus_sek| Command::KanalBearbeiten {
+            id: kanal.id,
+            name: None,
+            thema: None,
+            max_clients: None,
+            sort_order: None,
+            langsammodus_sek: Some(langsammodus_sek),
+            bearbeitungsfrist_sek: None,
+        };
+
+        match executor.ausfuehren(bearbeiten(30), &session).await.unwrap() {
+            Response::Kanal(info) => assert_eq!(info.langsammodus_sek, 30),
+            andere => panic!("unerwartet: {andere:?}"),
+        }
+        assert_eq!(
+            *signaling.geaenderte_kanaele.lock().unwrap(),
+            vec![kanal.id]
+        );
+
+        let zu_lang = executor
+            .ausfuehren(bearbeiten(MAX_LANGSAMMODUS_SEK + 1), &session)
+            .await;
+        assert!(matches!(zu_lang, Err(CommanderError::UngueltigeEingabe(_))));
+        let gespeichert = ChannelRepository::get_by_id(db.as_ref(), kanal.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(gespeichert.slow_mode_secs, 30);
+    }
+
+    #[tokio::test]
+    async fn bearbeitungsfrist_setzen_und_zuruecksetzen() {
+        let db = Arc::new(speakeasy_db::SqliteDb::in_memory().await.unwrap());
+        let executor = test_executor(&db);
+        let session = admin_session(&db).await;
+        let kanal = ChannelRepository::create(
+            db.as_ref(),
+            NeuerKanal {
+                name: "Plaudern",
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        let bearbeiten = |bearbeitungsfrist_sek| Command::KanalBearbeiten {
+            id: kanal.id,
+            name: None,
+            thema: None,
+            max_clients: None,
+            sort_order: None,
+            langsammodus_sek: None,
+            bearbeitungsfrist_sek,
+        };
+
+        match executor
+            .ausfuehren(bearbeiten(Some(Some(900))), &session)
+            .await
+            .unwrap()
+        {
+            Response::Kanal(info) => assert_eq!(info.bearbeitungsfrist_sek, Some(900)),
+            andere => panic!("unerwartet: {andere:?}"),
+        }
+        let zu_lang = executor
+            .ausfuehren(
+                bearbeiten(Some(Some(MAX_BEARBEITUNGSFRIST_SEK + 1))),
+                &session,
+            )
+            .await;
+        assert!(matches!(zu_lang, Err(CommanderError::UngueltigeEingabe(_))));
+
+        match executor
+            .ausfuehren(bearbeiten(Some(None)), &session)
+            .await
+            .unwrap()
+        {
+            Response::Kanal(info) => assert_eq!(info.bearbeitungsfrist_sek, None),
+            andere => panic!("unerwartet: {andere:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn nachricht_info_zeigt_bearbeitungen() {
+        use speakeasy_db::models::{NachrichtenTyp, NeueNachricht};
+
+        let db = Arc::new(speakeasy_db::SqliteDb::in_memory().await.unwrap());
+        let executor = test_executor(&db);
+        let session = admin_session(&db).await;
+        let sink = Arc::new(AufzeichnenderSink::default());
+        executor.audit_sink_setzen(Arc::clone(&sink) as Arc<dyn AuditSink>);
+        let kanal = ChannelRepository::create(
+            db.as_ref(),
+            NeuerKanal {
+                name: "Plaudern",
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        let nachricht = ChatMessageRepository::create(
+            db.as_ref(),
+            NeueNachricht {
+                channel_id: kanal.id,
+                sender_id: session.benutzer.id,
+                content: "eins",
+                message_type: NachrichtenTyp::Text,
+                reply_to: None,
+            },
+        )
+        .await
+        .unwrap();
+        for inhalt in ["zwei", "drei"] {
+            ChatMessageRepository::update_content(db.as_ref(), nachricht.id, inhalt)
+                .await
+                .unwrap();
+        }
+
+        let abfrage = |nachricht_id| Command::NachrichtInfo { nachricht_id };
+        match executor
+            .ausfuehren(abfrage(nachricht.id), &session)
+            .await
+            .unwrap()
+        {
+            Response::Nachricht(info) => {
+                assert_eq!(info.bearbeitungen, 2);
+                assert_eq!(info.inhalt, "drei");
+                assert_eq!(info.kanal_id, kanal.id);
+                assert!(info.bearbeitet_am.is_some());
+            }
+            andere => panic!("unerwartet: {andere:?}"),
+        }
+        assert_eq!(
+            *sink.gepuffert.lock().unwrap(),
+            vec!["chat.nachricht_abgefragt"]
+        );
+
+        let unbekannt = executor.ausfuehren(abfrage(Uuid::new_v4()), &session).await;
+        assert!(matches!(unbekannt, Err(CommanderError::NichtGefunden(_))));
+    }
+
+    #[tokio::test]
+    async fn kick_erreicht_signaling_und_meldet_offline_ziele() {
+        let db = Arc::new(speakeasy_db::SqliteDb::in_memory().await.unwrap());
+        let executor = test_executor(&db);
+        let session = admin_session(&db).await;
+        let kick = |client_id| Command::ClientKicken {
+            client_id,
+            grund: Some("Spam".into()),
+        };
+
+        // Ohne Anbindung ist der Befehl nicht verfuegbar
+        let ohne = executor.ausfuehren(kick(Uuid::new_v4()), &session).await;
+        assert!(matches!(ohne, Err(CommanderError::Intern(_))));
+
+        let signaling = Arc::new(TestSignaling::default());
+        let verbunden = Uuid::new_v4();
+        signaling.verbunden.lock().unwrap().push(verbunden);
+        executor.signaling_setzen(Arc::clone(&signaling) as Arc<dyn SignalingNotifier>);
+        let sink = Arc::new(AufzeichnenderSink::default());
+        executor.audit_sink_setzen(Arc::clone(&sink) as Arc<dyn AuditSink>);
+
+        executor
+            .ausfuehren(kick(verbunden), &session)
+            .await
+            .unwrap();
+        assert_eq!(
+            *signaling.gekickt.lock().unwrap(),
+            vec![(verbunden, Some("Spam".to_string()))]
+        );
+
+        // Zweiter Kick: der Client ist nicht mehr verbunden
+        let erneut = executor.ausfuehren(kick(verbunden), &session).await;
+        assert!(matches!(erneut, Err(CommanderError::NichtGefunden(_))));
+        let poke = executor
+            .ausfuehren(
+                Command::ClientPoken {
+                    client_id: verbunden,
+                    nachricht: "Hallo".into(),
+                },
+                &session,
+            )
+            .await;
+        assert!(matches!(poke, Err(CommanderError::NichtGefunden(_))));
+        assert_eq!(*sink.gepuffert.lock().unwrap(), vec!["client.gekickt"]);
+    }
+
+    #[tokio::test]
+    async fn soundboard_registrieren_kodiert_einmalig() {
+        use speakeasy_db::models::NeueDatei;
+
+        let db = Arc::new(speakeasy_db::SqliteDb::in_memory().await.unwrap());
+        let executor = test_executor(&db);
+        let session = admin_session(&db).await;
+        let kanal = ChannelRepository::create(
+            db.as_ref(),
+            NeuerKanal {
+                name: "Event",
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        let datei = FileRepository::create(
+            db.as_ref(),
+            NeueDatei {
+                channel_id: kanal.id,
+                uploader_id: session.benutzer.id,
+                filename: "gong.wav",
+                mime_type: "audio/wav",
+                size_bytes: 44,
+                storage_path: "event/gong.wav",
+                checksum: "00",
+            },
+        )
+        .await
+        .unwrap();
+        let registrieren = |name: &str| Command::SoundboardRegistrieren {
+            name: name.into(),
+            datei_id: datei.id,
+            kanal_id: Some(kanal.id),
+        };
+
+        // Ohne Kodierer ist der Befehl nicht verfuegbar
+        let ohne = executor.ausfuehren(registrieren("Gong"), &session).await;
+        assert!(matches!(ohne, Err(CommanderError::Intern(_))));
+
+        // Test-Kodierer: "lang" steht fuer eine Datei ueber der Hoechstdauer
+        let aufrufe = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let protokoll = Arc::clone(&aufrufe);
+        executor.soundboard_kodierer_setzen(Arc::new(move |pfad: String, max_dauer| {
+            protokoll.lock().unwrap().push((pfad.clone(), max_dauer));
+            Box::pin(async move {
+                if pfad.contains("lang") {
+                    return Err(CommanderError::UngueltigeEingabe("Clip zu lang".into()));
+                }
+                Ok(KodierterSound {
+                    frames: vec![vec![1; 40]; 3],
+                    frame_ms: 20,
+                })
+            })
+        }));
+
+        let Response::Sound(sound) = executor
+            .ausfuehren(registrieren("  Gong "), &session)
+            .await
+            .unwrap()
+        else {
+            panic!("Sound erwartet");
+        };
+        assert_eq!(sound.name, "Gong");
+        assert_eq!(sound.dauer_ms, 60);
+        assert_eq!(sound.kanal_id, Some(kanal.id));
+        assert_eq!(
+            *aufrufe.lock().unwrap(),
+            vec![("event/gong.wav".to_string(), SOUNDBOARD_MAX_DAUER)]
+        );
+        assert_eq!(
+            SoundboardRepository::get_frames(db.as_ref(), sound.id)
+                .await
+                .unwrap()
+                .unwrap()
+                .len(),
+            3
+        );
+
+        // Doppelter Name und leerer Name sind Eingabefehler
+        let doppelt = executor.ausfuehren(registrieren("Gong"), &session).await;
+        assert!(matches!(doppelt, Err(CommanderError::UngueltigeEingabe(_))));
+        let leer = executor.ausfuehren(registrieren("  "), &session).await;
+        assert!(matches!(leer, Err(CommanderError::UngueltigeEingabe(_))));
+
+        let Response::SoundboardListe(liste) = executor
+            .ausfuehren(
+                Command::SoundboardListe {
+                    kanal_id: Some(kanal.id),
+                },
+                &session,
+            )
+            .await
+            .unwrap()
+        else {
+            panic!("Liste erwartet");
+        };
+        assert_eq!(liste.len(), 1);
+
+        let entfernen = Command::SoundboardEntfernen { id: sound.id };
+        executor
+            .ausfuehren(entfernen.clone(), &session)
+            .await
+            .unwrap();
+        let erneut = executor.ausfuehren(entfernen, &session).await;
+        assert!(matches!(erneut, Err(CommanderError::NichtGefunden(_))));
+    }
+
+    async fn kanal_seite(
+        executor: &TestExecutor,
+        session: &CommanderSession,
+        name: Option<&str>,
+        seite: u32,
+        groesse: u32,
+    ) -> KanalSeite {
+        let cmd = Command::KanalListe {
+            name: name.map(String::from),
+            seite: SeitenAnfrage::neu(Some(seite), Some(groesse)),
+        };
+        match executor.ausfuehren(cmd, session).await.unwrap() {
+            Response::KanalListe(seite) => seite,
+            andere => panic!("unerwartet: {andere:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn kanalliste_seitenweise_und_gefiltert() {
+        let db = Arc::new(speakeasy_db::SqliteDb::in_memory().await.unwrap());
+        let executor = test_executor(&db);
+        let session = admin_session(&db).await;
+        let vorher = ChannelRepository::list(db.as_ref()).await.unwrap().len() as u64;
+        for (i, name) in ["Raid 1", "Musik", "Raid 2", "Raid 3", "AFK"]
+            .into_iter()
+            .enumerate()
+        {
+            ChannelRepository::create(
+                db.as_ref(),
+                NeuerKanal {
+                    name,
+                    sort_order: i as i64 + 1,
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        }
+        let gesamt = vorher + 5;
+
+        // Seitengrenzen: die letzte Seite ist genau voll
+        let erste = kanal_seite(&executor, &session, None, 1, 1).await;
+        assert_eq!(erste.kanaele.len(), 1);
+        assert_eq!(erste.seite.total_count, gesamt);
+        assert!(erste.seite.has_next_page);
+        let letzte = kanal_seite(&executor, &session, None, gesamt as u32, 1).await;
+        assert_eq!(letzte.kanaele.len(), 1);
+        assert!(!letzte.seite.has_next_page);
+
+        // Ausserhalb des Bereichs: leer, Gesamtzahl stimmt trotzdem
+        let leer = kanal_seite(&executor, &session, None, 100, 10).await;
+        assert!(leer.kanaele.is_empty());
+        assert_eq!(leer.seite.total_count, gesamt);
+        assert!(!leer.seite.has_next_page);
+
+        // Filter und Seite kombiniert
+        let raid = kanal_seite(&executor, &session, Some("raid"), 1, 2).await;
+        let namen: Vec<_> = raid.kanaele.iter().map(|k| k.name.as_str()).collect();
+        assert_eq!(namen, ["Raid 1", "Raid 2"]);
+        assert_eq!(raid.seite.total_count, 3);
+        assert!(raid.seite.has_next_page);
+        let raid = kanal_seite(&executor, &session, Some("raid"), 2, 2).await;
+        assert_eq!(raid.kanaele.len(), 1);
+        assert_eq!(raid.kanaele[0].name, "Raid 3");
+        assert!(!raid.seite.has_next_page);
+    }
+
+    async fn benutzer_anlegen(db: &speakeasy_db::SqliteDb, name: &str) -> BenutzerRecord {
+        UserRepository::create(
+            db,
+            speakeasy_db::models::NeuerBenutzer {
+                username: name,
+                password_hash: "hash",
+            },
+        )
+        .await
+        .unwrap()
+    }
+
+    async fn benutzer_seite(
+        executor: &TestExecutor,
+        session: &CommanderSession,
+        filter: Option<&str>,
+        limit: u32,
+        offset: u32,
+    ) -> BenutzerSeite {
+        let cmd = Command::BenutzerListe {
+            filter: filter.map(Into::into),
+            limit,
+            offset,
+        };
+        match executor.ausfuehren(cmd, session).await.unwrap() {
+            Response::BenutzerListe(seite) => seite,
+            andere => panic!("Erwartet BenutzerListe, erhalten {andere:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn benutzerverwaltung_wird_sicher_protokolliert() {
+        let db = Arc::new(speakeasy_db::SqliteDb::in_memory().await.unwrap());
+        let executor = test_executor(&db);
+        let sink = Arc::new(AufzeichnenderSink::default());
+        executor.audit_sink_setzen(Arc::clone(&sink) as Arc<dyn AuditSink>);
+        let session = admin_session(&db).await;
+        let anna = benutzer_anlegen(&db, "Anna").await;
+        benutzer_anlegen(&db, "Annika").await;
+        benutzer_anlegen(&db, "Bernd").await;
+
+        // Filter ohne Gross-/Kleinschreibung, Seite nach dem Filtern
+        let seite = benutzer_seite(&executor, &session, Some("ANN"), 1, 1).await;
+        assert_eq!(seite.gesamt, 2);
+        assert_eq!(seite.benutzer[0].username, "Annika");
+        assert_eq!(
+            benutzer_seite(&executor, &session, None, 50, 0)
+                .await
+                .gesamt,
+            4
+        );
+
+        let zu_kurz = executor
+            .ausfuehren(
+                Command::BenutzerPasswortSetzen {
+                    user_id: anna.id,
+                    neues_passwort: "kurz".into(),
+                },
+                &session,
+            )
+            .await
+            .unwrap_err();
+        assert!(matches!(zu_kurz, CommanderError::UngueltigeEingabe(_)));
+        let Response::Benutzer(info) = executor
+            .ausfuehren(
+                Command::BenutzerPasswortSetzen {
+                    user_id: anna.id,
+                    neues_passwort: "neues-passwort".into(),
+                },
+                &session,
+            )
+            .await
+            .unwrap()
+        else {
+            panic!("Erwartet Benutzer");
+        };
+        assert!(!info.passwort_geaendert);
+        let gespeichert = UserRepository::get_by_id(db.as_ref(), anna.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(speakeasy_auth::passwort_verifizieren(
+            "neues-passwort",
+            &gespeichert.password_hash
+        )
+        .unwrap());
+
+        for deaktiviert in [true, false] {
+            let Response::Benutzer(info) = executor
+                .ausfuehren(
+                    Command::BenutzerDeaktivieren {
+                        user_id: anna.id,
+                        deaktiviert,
+                    },
+                    &session,
+                )
+                .await
+                .unwrap()
+            else {
+                panic!("Erwartet Benutzer");
+            };
+            assert_eq!(info.aktiv, !deaktiviert);
+        }
+
+        executor
+            .ausfuehren(Command::BenutzerLoeschen { user_id: anna.id }, &session)
+            .await
+            .unwrap();
+        let seite = benutzer_seite(&executor, &session, Some("anna"), 50, 0).await;
+        assert!(!seite.benutzer[0].aktiv);
+
+        // Unbekannte Benutzer und das eigene Konto
+        let fehler = executor
+            .ausfuehren(
+                Command::BenutzerLoeschen {
+                    user_id: Uuid::new_v4(),
+                },
+                &session,
+            )
+            .await
+            .unwrap_err();
+        assert!(matches!(fehler, CommanderError::NichtGefunden(_)));
+        let fehler = executor
+            .ausfuehren(
+                Command::BenutzerDeaktivieren {
+                    user_id: session.benutzer.id,
+                    deaktiviert: true,
+                },
+                &session,
+            )
+            .await
+            .unwrap_err();
+        assert!(matches!(fehler, CommanderError::UngueltigeEingabe(_)));
+
+        assert!(sink.gepuffert.lock().unwrap().is_empty());
+        assert_eq!(
+            *sink.sicher.lock().unwrap(),
+            vec![
+                "benutzer.passwort_gesetzt",
+                "benutzer.gesperrt",
+                "benutzer.freigeschaltet",
+                "benutzer.entfernt"
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn letzter_administrator_bleibt_erhalten() {
+        let db = Arc::new(speakeasy_db::SqliteDb::in_memory().await.unwrap());
+        let executor = test_executor(&db);
+        let session = admin_session(&db).await;
+        let chefin = benutzer_anlegen(&db, "chefin").await;
+        let vertretung = benutzer_anlegen(&db, "vertretung").await;
+        let gast = benutzer_anlegen(&db, "gast").await;
+        let gruppe = speakeasy_db::ServerGroupRepository::create(
+            db.as_ref(),
+            speakeasy_db::models::NeueServerGruppe {
+                name: "Server Admin",
+                priority: 100,
+                is_default: false,
+            },
+        )
+        .await
+        .unwrap();
+        db.set_permission(
+            &BerechtigungsZiel::ServerGruppe(gruppe.id),
+            ADMIN_BERECHTIGUNG,
+            BerechtigungsWert::TriState(TriState::Grant),
+            None,
+        )
+        .await
+        .unwrap();
+        for admin in [&chefin, &vertretung] {
+            speakeasy_db::ServerGroupRepository::add_member(db.as_ref(), gruppe.id, admin.id)
+                .await
+                .unwrap();
+        }
+
+        let seite = benutzer_seite(&executor, &session, None, 50, 0).await;
+        let admins: Vec<_> = seite
+            .benutzer
+            .iter()
+            .filter(|b| b.administrator)
+            .map(|b| b.username.as_str())
+            .collect();
+        assert_eq!(admins, ["chefin", "vertretung"]);
+
+        // Solange ein zweiter Administrator bleibt, ist das Entfernen erlaubt
+        executor
+            .ausfuehren(
+                Command::BenutzerLoeschen {
+                    user_id: vertretung.id,
+                },
+                &session,
+            )
+            .await
+            .unwrap();
+        for cmd in [
+            Command::BenutzerLoeschen { user_id: chefin.id },
+            Command::BenutzerDeaktivieren {
+                user_id: chefin.id,
+                deaktiviert: true,
+            },
+        ] {
+            let fehler = executor.ausfuehren(cmd, &session).await.unwrap_err();
+            assert!(matches!(fehler, CommanderError::UngueltigeEingabe(_)));
+        }
+        assert!(
+            UserRepository::get_by_id(db.as_ref(), chefin.id)
+                .await
+                .unwrap()
+                .unwrap()
+                .is_active
+        );
+
+        // Nicht-Administratoren sind nicht betroffen
+        executor
+            .ausfuehren(Command::BenutzerLoeschen { user_id: gast.id }, &session)
+            .await
+            .unwrap();
+    }
+
+    #[test]
+    fn client_seite_filtert_vor_dem_zuschneiden() {
+        let lobby = Uuid::new_v4();
+        let client = |name: &str, kanal_id| ClientInfo {
+            user_id: Uuid::new_v4(),
+            username: name.into(),
+            kanal_id,
+            verbunden_seit_ms: 0,
+            ist_gemutet: false,
+            ist_gehoerlos: false,
+            ip_adresse: None,
+        };
+        let clients = vec![
+            client("Anna", Some(lobby)),
+            client("Bernd", None),
+            client("Annika", Some(lobby)),
+            client("Hannes", Some(lobby)),
+            client("Joanna", Some(Uuid::new_v4())),
+        ];
+
+        let seite = client_seite(
+            clients.clone(),
+            Some(lobby),
+            Some("ANN"),
+            SeitenAnfrage::neu(Some(2), Some(2)),
+        );
+        let namen: Vec<_> = seite.clients.iter().map(|c| c.username.as_str()).collect();
+        assert_eq!(namen, ["Hannes"]);
+        assert_eq!(seite.seite.total_count, 3);
+        assert!(!seite.seite.has_next_page);
+
+        let leer = client_seite(clients, None, None, SeitenAnfrage::neu(Some(3), Some(5)));
+        assert!(leer.clients.is_empty());
+        assert_eq!(leer.seite.total_count, 5);
+    }
+
     #[test]
     fn db_wert_konvertierung_int_limit() {
         let input = BerechtigungsWertInput::IntLimit(42);
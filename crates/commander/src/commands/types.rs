@@ -1,27 +1,67 @@
 //! Command- und Response-Typen fuer den einheitlichen Befehlsausführer
 
+use std::time::Duration;
+
 use serde::{Deserialize, Serialize};
+use speakeasy_db::{
+    einstellungen::ServerEinstellungen, models::GeplanteAktion, motd::Motd, zeitlimit::Zugriffsart,
+};
+use speakeasy_observability::alarm::{AlarmMeldung, AlarmStatus};
 use uuid::Uuid;
 
+use crate::rest::typen::BackupGestartet;
+use crate::sicherung::SicherungsFortschritt;
+
 /// Alle unterstuetzten Commander-Befehle
 #[derive(Debug, Clone, PartialEq)]
 pub enum Command {
     // --- Server ---
     /// Server-Informationen abrufen
     ServerInfo,
+    /// Die letzten `limit` Serverstarts (neueste zuerst)
+    ServerHistorie { limit: u32 },
     /// Server-Konfiguration aendern
     ServerEdit {
         name: Option<String>,
         willkommensnachricht: Option<String>,
         max_clients: Option<u32>,
         host_nachricht: Option<String>,
+        /// Inaktivitaet in Sekunden bis zur AFK-Verschiebung (0 = deaktiviert)
+        afk_timeout_sek: Option<u32>,
+        /// Ziel-Kanal fuer inaktive Clients
+        afk_kanal_id: Option<Uuid>,
+        /// Standard-Frist fuer das Bearbeiten und Loeschen eigener
+        /// Chat-Nachrichten in Sekunden (0 = unbegrenzt)
+        chat_bearbeitungsfrist_sek: Option<u32>,
     },
     /// Server stoppen
     ServerStop { grund: Option<String> },
+    /// Sicherung von Datenbank, Konfiguration und optional Dateien anlegen
+    ///
+    /// Laeuft im Hintergrund; der Fortschritt wird als
+    /// [`CommanderEreignis::Sicherung`] gemeldet.
+    BackupErstellen {
+        /// Verzeichnis auf dem Server (darf nicht existieren oder muss leer sein)
+        ziel_pfad: String,
+        include_files: bool,
+    },
+    /// Nachricht des Tages setzen (Markdown, leer = entfernen)
+    ///
+    /// Die Version steigt nur bei einer inhaltlichen Aenderung; dann folgt
+    /// [`CommanderEreignis::MotdGeaendert`].
+    MotdSetzen { markdown: String },
+    /// Alle Alarmregeln sofort auswerten und ihren Stand melden
+    ///
+    /// Dabei faellige Meldungen werden wie bei der periodischen Auswertung
+    /// zugestellt.
+    AlertRegelnTesten,
 
     // --- Kanaele ---
-    /// Kanalliste abrufen
-    KanalListe,
+    /// Kanalliste seitenweise abrufen, optional nach Namensbestandteil gefiltert
+    KanalListe {
+        name: Option<String>,
+        seite: SeitenAnfrage,
+    },
     /// Kanal erstellen
     KanalErstellen {
         name: String,
@@ -39,13 +79,43 @@ pub enum Command {
         thema: Option<Option<String>>,
         max_clients: Option<i64>,
         sort_order: Option<i64>,
+        /// Langsam-Modus in Sekunden (0 = aus)
+        langsammodus_sek: Option<u32>,
+        /// Bearbeitungsfrist in Sekunden (0 = unbegrenzt, `Some(None)` =
+        /// Server-Standard)
+        bearbeitungsfrist_sek: Option<Option<u32>>,
     },
     /// Kanal loeschen
     KanalLoeschen { id: Uuid },
+    /// Kompletten Kanalbaum aus einer Vorlage anlegen (alles-oder-nichts)
+    KanalbaumAusVorlage {
+        vorlage_id: Uuid,
+        parent_id: Option<Uuid>,
+        name_prefix: Option<String>,
+    },
+    /// Notfall-Stummschaltung: alle ausser ausgenommenen Moderatoren stumm
+    KanalNotfallStumm { kanal_id: Uuid, aktivieren: bool },
+
+    // --- Kanal-Vorlagen ---
+    /// Kanal-Vorlagen auflisten
+    VorlageListe,
+    /// Kanal-Vorlage anlegen (`definition` = JSON-Wurzelknoten)
+    VorlageErstellen {
+        name: String,
+        beschreibung: Option<String>,
+        definition: serde_json::Value,
+    },
+    /// Kanal-Vorlage loeschen
+    VorlageLoeschen { id: Uuid },
 
     // --- Clients ---
-    /// Liste verbundener Clients (nur ephemere Daten)
-    ClientListe,
+    /// Liste verbundener Clients (nur ephemere Daten), seitenweise und
+    /// optional nach Kanal und Namensbestandteil gefiltert
+    ClientListe {
+        kanal_id: Option<Uuid>,
+        username: Option<String>,
+        seite: SeitenAnfrage,
+    },
     /// Client kicken
     ClientKicken {
         client_id: Uuid,
@@ -57,11 +127,63 @@ pub enum Command {
         dauer_secs: Option<u64>,
         grund: Option<String>,
         ip_bannen: bool,
+        /// Nachrichten und Dateien aus diesem Zeitraum entfernen (None = behalten)
+        inhalte_entfernen: Option<Duration>,
     },
     /// Client in anderen Kanal verschieben
     ClientVerschieben { client_id: Uuid, kanal_id: Uuid },
+    /// Alle (oder ausgewaehlte) Clients eines Kanals gemeinsam verschieben
+    ClientsVerschiebenAlle {
+        von_kanal_id: Uuid,
+        nach_kanal_id: Uuid,
+        /// Nur diese Benutzer (None = alle im Quellkanal)
+        nur_user_ids: Option<Vec<Uuid>>,
+        /// Bei zu wenig Platz im Ziel so viele wie moeglich verschieben
+        /// statt abzulehnen
+        teilweise: bool,
+    },
+    /// Clients eines Kanals, die gerade sprechen
+    AktiveSprecher { kanal_id: Uuid },
+    /// Voice-Diagnose eines Clients (Netzwerk und geschaetzte Sprachqualitaet)
+    VoiceDiagnose { client_id: Uuid },
     /// Client anpiken (Poke)
     ClientPoken { client_id: Uuid, nachricht: String },
+    /// Nachrichten und Dateien eines Benutzers entfernen (ohne Bann)
+    BenutzerInhalteBereinigen {
+        user_id: Uuid,
+        /// Rueckblick ab jetzt
+        zeitraum: Duration,
+        /// Nur in diesem Kanal (None = alle Kanaele)
+        nur_kanal: Option<Uuid>,
+        /// Abgebrochenen Auftrag fortsetzen; Zeitraum und Kanal stammen dann
+        /// aus dem Auftrag
+        job_id: Option<Uuid>,
+    },
+
+    // --- Konten ---
+    /// Datenexport eines Benutzers erstellen (ohne Sperrfrist, fuer Support-Faelle)
+    KontoExport { benutzer_id: Uuid },
+    /// Konto eines Benutzers endgueltig loeschen (trennt ihn, falls verbunden)
+    KontoLoeschen { benutzer_id: Uuid },
+
+    // --- Benutzerverwaltung ---
+    /// Registrierte Benutzer auflisten (auch gesperrte), optional nach
+    /// Namensbestandteil gefiltert
+    BenutzerListe {
+        filter: Option<String>,
+        limit: u32,
+        offset: u32,
+    },
+    /// Passwort eines Benutzers neu setzen (beendet seine Sessions)
+    BenutzerPasswortSetzen {
+        user_id: Uuid,
+        neues_passwort: String,
+    },
+    /// Benutzer sperren (`deaktiviert = false`: wieder freischalten)
+    BenutzerDeaktivieren { user_id: Uuid, deaktiviert: bool },
+    /// Benutzer entfernen: Anmeldung gesperrt, Sessions und API-Tokens
+    /// widerrufen, Inhalte bleiben (endgueltig mit Daten: [`Command::KontoLoeschen`])
+    BenutzerLoeschen { user_id: Uuid },
 
     // --- Berechtigungen ---
     /// Berechtigungen fuer ein Ziel abfragen
@@ -79,12 +201,43 @@ pub enum Command {
         permission: String,
         scope: String,
     },
+    /// Effektive Berechtigungen eines Benutzers mit Herkunft (ohne Kanal: Server-Root)
+    BerechtigungEffektiv {
+        user_id: Uuid,
+        kanal_id: Option<Uuid>,
+    },
 
     // --- Dateien ---
     /// Dateien eines Kanals auflisten
     DateiListe { kanal_id: Uuid },
     /// Datei loeschen
     DateiLoeschen { datei_id: String },
+    /// Download-Zugriffsprotokoll abfragen (nach Datei und/oder Benutzer)
+    DateiZugriffe {
+        datei_id: Option<Uuid>,
+        benutzer_id: Option<Uuid>,
+        von: Option<chrono::DateTime<chrono::Utc>>,
+        bis: Option<chrono::DateTime<chrono::Utc>>,
+        limit: u32,
+        offset: u32,
+    },
+
+    // --- Chat ---
+    /// Eine Chat-Nachricht mit Bearbeitungszaehler abrufen (auch geloeschte)
+    NachrichtInfo { nachricht_id: Uuid },
+
+    // --- Soundboard ---
+    /// Sounds auflisten (mit Kanal: serverweite und die des Kanals)
+    SoundboardListe { kanal_id: Option<Uuid> },
+    /// Hochgeladene Datei einmalig kodieren und als Sound registrieren
+    SoundboardRegistrieren {
+        name: String,
+        datei_id: Uuid,
+        /// Nur in diesem Kanal abspielbar (None = serverweit)
+        kanal_id: Option<Uuid>,
+    },
+    /// Sound entfernen (laufende Wiedergaben spielen zu Ende)
+    SoundboardEntfernen { id: Uuid },
 
     // --- Logs ---
     /// Audit-Log abfragen
@@ -93,6 +246,21 @@ pub enum Command {
         offset: u32,
         aktion_filter: Option<String>,
     },
+
+    // --- Zeitplaner ---
+    /// Geplante Aktionen auflisten
+    ZeitplanListe,
+    /// Aktion planen: entweder `einmalig` (UTC) oder wiederkehrend per `cron`
+    ZeitplanErstellen {
+        name: String,
+        aktion: GeplanteAktion,
+        einmalig: Option<chrono::DateTime<chrono::Utc>>,
+        cron: Option<String>,
+        /// IANA-Zeitzone, in der `cron` ausgewertet wird (Standard: UTC)
+        zeitzone: Option<String>,
+    },
+    /// Geplante Aktion abbrechen
+    ZeitplanAbbrechen { id: Uuid },
 }
 
 impl Command {
@@ -104,32 +272,114 @@ impl Command {
         match self {
             // Lesende Server-Befehle
             Command::ServerInfo => "cmd:serverinfo",
+            Command::ServerHistorie { .. } => "cmd:serverinfo",
             // Schreibende Server-Befehle
             Command::ServerEdit { .. } => "cmd:serveredit",
             Command::ServerStop { .. } => "cmd:serverstop",
+            // Sicherung (eigener Admin-Scope, nicht von "cmd:*" abgedeckt)
+            Command::BackupErstellen { .. } => "admin:backup",
+            // MOTD (Admin-Scope, nicht von "cmd:*" abgedeckt)
+            Command::MotdSetzen { .. } => "admin:server:write",
+            // Alarmregeln (Admin-Scope, nicht von "cmd:*" abgedeckt)
+            Command::AlertRegelnTesten => "admin:alerts",
             // Kanal-Lesebefehle
-            Command::KanalListe => "cmd:channellist",
+            Command::KanalListe { .. } => "cmd:channellist",
             // Kanal-Schreibbefehle
             Command::KanalErstellen { .. } => "cmd:channelcreate",
             Command::KanalBearbeiten { .. } => "cmd:channeledit",
             Command::KanalLoeschen { .. } => "cmd:channeldelete",
+            Command::KanalbaumAusVorlage { .. } => "cmd:channelcreate",
+            Command::KanalNotfallStumm { .. } => "cmd:channelmute",
+            // Vorlagen-Befehle
+            Command::VorlageListe => "cmd:templatelist",
+            Command::VorlageErstellen { .. } => "cmd:templatewrite",
+            Command::VorlageLoeschen { .. } => "cmd:templatewrite",
             // Client-Lesebefehle
-            Command::ClientListe => "cmd:clientlist",
+            Command::ClientListe { .. } => "cmd:clientlist",
+            Command::AktiveSprecher { .. } => "cmd:clientlist",
+            Command::VoiceDiagnose { .. } => "cmd:clientlist",
             // Client-Aktionsbefehle
             Command::ClientKicken { .. } => "cmd:clientkick",
             Command::ClientBannen { .. } => "cmd:clientban",
             Command::ClientVerschieben { .. } => "cmd:clientmove",
+            Command::ClientsVerschiebenAlle { .. } => "cmd:clientmove",
             Command::ClientPoken { .. } => "cmd:clientpoke",
+            Command::BenutzerInhalteBereinigen { .. } => "cmd:contentcleanup",
+            // Konten (eigener Admin-Scope, nicht von "cmd:*" abgedeckt)
+            Command::KontoExport { .. } | Command::KontoLoeschen { .. } => "admin:accounts",
+            // Benutzerverwaltung (Admin-Scopes, nicht von "cmd:*" abgedeckt)
+            Command::BenutzerListe { .. } => "admin:users:read",
+            Command::BenutzerPasswortSetzen { .. }
+            | Command::BenutzerDeaktivieren { .. }
+            | Command::BenutzerLoeschen { .. } => "admin:users:write",
             // Berechtigungs-Lesebefehle
             Command::BerechtigungListe { .. } => "cmd:permissionlist",
+            Command::BerechtigungEffektiv { .. } => "cmd:permissionlist",
             // Berechtigungs-Schreibbefehle
             Command::BerechtigungSetzen { .. } => "cmd:permissionwrite",
             Command::BerechtigungEntfernen { .. } => "cmd:permissionwrite",
             // Datei-Befehle
             Command::DateiListe { .. } => "cmd:filelist",
             Command::DateiLoeschen { .. } => "cmd:filedelete",
+            Command::DateiZugriffe { .. } => "cmd:fileaccesslog",
+            // Chat-Befehle
+            Command::NachrichtInfo { .. } => "cmd:messageinfo",
+            // Soundboard-Befehle
+            Command::SoundboardListe { .. } => "cmd:soundboardlist",
+            Command::SoundboardRegistrieren { .. } => "cmd:soundboardwrite",
+            Command::SoundboardEntfernen { .. } => "cmd:soundboardwrite",
             // Log-Befehle
             Command::LogAbfragen { .. } => "cmd:logview",
+            // Zeitplaner (eigener Admin-Scope, nicht von "cmd:*" abgedeckt)
+            Command::ZeitplanListe
+            | Command::ZeitplanErstellen { .. }
+            | Command::ZeitplanAbbrechen { .. } => "admin:schedule",
+        }
+    }
+
+    /// Gibt zurueck ob der Befehl nur liest oder Zustand veraendert
+    ///
+    /// Bestimmt Zeitlimit und Abbruchverhalten (siehe `speakeasy_db::zeitlimit`).
+    pub fn zugriffsart(&self) -> Zugriffsart {
+        match self {
+            Command::ServerInfo
+            | Command::ServerHistorie { .. }
+            | Command::KanalListe { .. }
+            | Command::VorlageListe
+            | Command::ClientListe { .. }
+            | Command::AktiveSprecher { .. }
+            | Command::VoiceDiagnose { .. }
+            | Command::BerechtigungListe { .. }
+            | Command::BerechtigungEffektiv { .. }
+            | Command::DateiListe { .. }
+            | Command::DateiZugriffe { .. }
+            | Command::NachrichtInfo { .. }
+            | Command::SoundboardListe { .. }
+            | Command::LogAbfragen { .. }
+            | Command::BenutzerListe { .. }
+            | Command::ZeitplanListe => Zugriffsart::Lesen,
+            // Alles andere wird vorsichtshalber als schreibend behandelt
+            _ => Zugriffsart::Schreiben,
+        }
+    }
+
+    /// Befehle, mit denen der Zeitplaner eine geplante Aktion ausfuehrt
+    pub fn aus_geplanter_aktion(aktion: &GeplanteAktion) -> Vec<Self> {
+        match aktion {
+            GeplanteAktion::KanalAusVorlage {
+                vorlage_id,
+                parent_id,
+                name_prefix,
+                ..
+            } => vec![Command::KanalbaumAusVorlage {
+                vorlage_id: *vorlage_id,
+                parent_id: *parent_id,
+                name_prefix: name_prefix.clone(),
+            }],
+            GeplanteAktion::KanaeleLoeschen { kanal_ids } => kanal_ids
+                .iter()
+                .map(|id| Command::KanalLoeschen { id: *id })
+                .collect(),
         }
     }
 
@@ -139,9 +389,16 @@ impl Command {
         matches!(
             self,
             Command::ClientBannen { .. }
+                | Command::BenutzerInhalteBereinigen { .. }
+                | Command::KanalbaumAusVorlage { .. }
                 | Command::BerechtigungSetzen { .. }
                 | Command::BerechtigungEntfernen { .. }
                 | Command::ServerStop { .. }
+                | Command::BackupErstellen { .. }
+                | Command::KontoExport { .. }
+                | Command::KontoLoeschen { .. }
+                | Command::BenutzerPasswortSetzen { .. }
+                | Command::SoundboardRegistrieren { .. }
         )
     }
 }
@@ -164,18 +421,109 @@ pub enum Response {
     Ok,
     /// Server-Informationen
     ServerInfo(ServerInfoResponse),
-    /// Kanalliste
-    KanalListe(Vec<KanalInfo>),
+    /// Neustart-Protokoll (neueste zuerst)
+    ServerHistorie(Vec<ServerStartInfo>),
+    /// Seite der Kanalliste
+    KanalListe(KanalSeite),
     /// Kanal-Detail
     Kanal(KanalInfo),
-    /// Client-Liste
-    ClientListe(Vec<ClientInfo>),
+    /// Neu angelegter Kanalbaum (Wurzel zuerst)
+    Kanalbaum(Vec<KanalInfo>),
+    /// Liste der Kanal-Vorlagen
+    VorlageListe(Vec<VorlageInfo>),
+    /// Kanal-Vorlage
+    Vorlage(VorlageInfo),
+    /// Seite der Client-Liste
+    ClientListe(ClientSeite),
+    /// User-IDs der gerade sprechenden Clients eines Kanals
+    AktiveSprecher(Vec<Uuid>),
+    /// Voice-Diagnose eines Clients
+    VoiceDiagnose(VoiceDiagnoseInfo),
+    /// Ergebnis eines Sammel-Moves
+    ClientsVerschoben(SammelVerschiebungErgebnis),
+    /// Stand der Notfall-Stummschaltung eines Kanals
+    NotfallStumm(NotfallStummErgebnis),
     /// Berechtigungsliste
     BerechtigungListe(Vec<BerechtigungsEintrag>),
+    /// Effektive Berechtigungen (ein Eintrag pro Key des Katalogs)
+    BerechtigungEffektiv(Vec<EffektiverBerechtigungsEintrag>),
     /// Dateiliste
     DateiListe(Vec<DateiEintrag>),
+    /// Seite aus dem Datei-Zugriffsprotokoll
+    DateiZugriffe(DateiZugriffSeite),
+    /// Chat-Nachricht mit Bearbeitungszaehler
+    Nachricht(NachrichtInfo),
+    /// Soundboard-Sounds
+    SoundboardListe(Vec<SoundInfo>),
+    /// Registrierter Sound
+    Sound(SoundInfo),
     /// Log-Eintraege
     LogEintraege(Vec<LogEintrag>),
+    /// Geplante Aktionen
+    ZeitplanListe(Vec<ZeitplanInfo>),
+    /// Geplante Aktion
+    Zeitplan(ZeitplanInfo),
+    /// Sicherung wurde gestartet (Fortschritt folgt als Ereignis)
+    BackupGestartet { ziel_pfad: String },
+    /// Gespeicherte Nachricht des Tages
+    Motd(Motd),
+    /// Stand aller Alarmregeln
+    AlarmRegeln(Vec<AlarmStatus>),
+    /// Fertiger Datenexport eines Kontos
+    KontoExport(KontoExportErgebnis),
+    /// Ergebnis einer Kontoloeschung
+    KontoGeloescht(KontoLoeschErgebnis),
+    /// Ergebnis einer Inhaltsbereinigung
+    InhalteBereinigt(BereinigungsErgebnis),
+    /// Seite der Benutzerliste
+    BenutzerListe(BenutzerSeite),
+    /// Benutzer nach einer Aenderung
+    Benutzer(BenutzerInfo),
+}
+
+impl Response {
+    /// Nutzlast ohne `typ`-Markierung, wie sie die REST-API ausliefert
+    ///
+    /// Listen werden zu JSON-Arrays (intern getaggt waeren sie nicht
+    /// serialisierbar), `Ok` wird zu `null`.
+    pub fn nutzlast(&self) -> serde_json::Result<serde_json::Value> {
+        use serde_json::to_value;
+        match self {
+            Self::Ok => Ok(serde_json::Value::Null),
+            Self::ServerInfo(info) => to_value(info),
+            Self::ServerHistorie(starts) => to_value(starts),
+            Self::KanalListe(seite) => to_value(seite),
+            Self::Kanalbaum(kanaele) => to_value(kanaele),
+            Self::Kanal(kanal) => to_value(kanal),
+            Self::VorlageListe(vorlagen) => to_value(vorlagen),
+            Self::Vorlage(vorlage) => to_value(vorlage),
+            Self::ClientListe(seite) => to_value(seite),
+            Self::AktiveSprecher(sprecher) => to_value(sprecher),
+            Self::VoiceDiagnose(diagnose) => to_value(diagnose),
+            Self::ClientsVerschoben(ergebnis) => to_value(ergebnis),
+            Self::NotfallStumm(ergebnis) => to_value(ergebnis),
+            Self::BerechtigungListe(eintraege) => to_value(eintraege),
+            Self::BerechtigungEffektiv(eintraege) => to_value(eintraege),
+            Self::DateiListe(dateien) => to_value(dateien),
+            Self::DateiZugriffe(seite) => to_value(seite),
+            Self::Nachricht(nachricht) => to_value(nachricht),
+            Self::SoundboardListe(sounds) => to_value(sounds),
+            Self::Sound(sound) => to_value(sound),
+            Self::LogEintraege(eintraege) => to_value(eintraege),
+            Self::ZeitplanListe(zeitplaene) => to_value(zeitplaene),
+            Self::Zeitplan(zeitplan) => to_value(zeitplan),
+            Self::BackupGestartet { ziel_pfad } => to_value(BackupGestartet {
+                ziel_pfad: ziel_pfad.clone(),
+            }),
+            Self::Motd(motd) => to_value(motd),
+            Self::AlarmRegeln(regeln) => to_value(regeln),
+            Self::KontoExport(export) => to_value(export),
+            Self::KontoGeloescht(ergebnis) => to_value(ergebnis),
+            Self::InhalteBereinigt(ergebnis) => to_value(ergebnis),
+            Self::BenutzerListe(seite) => to_value(seite),
+            Self::Benutzer(benutzer) => to_value(benutzer),
+        }
+    }
 }
 
 /// Server-Informationen fuer Antworten
@@ -187,6 +535,25 @@ pub struct ServerInfoResponse {
     pub aktuelle_clients: u32,
     pub version: String,
     pub uptime_secs: u64,
+    pub host_nachricht: Option<String>,
+    /// Einkompilierte optionale Subsysteme (`plugins`, `grpc`, `observability`,
+    /// `tcp-commander`)
+    #[serde(default)]
+    pub features: Vec<String>,
+    /// Der Lauf vor diesem Start endete nicht geordnet (Absturz, kill -9)
+    #[serde(default)]
+    pub letzter_shutdown_unsauber: bool,
+}
+
+/// Eintrag im Neustart-Protokoll
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ServerStartInfo {
+    pub gestartet_um: chrono::DateTime<chrono::Utc>,
+    pub version: String,
+    /// Ob der vorherige Lauf sauber heruntergefahren wurde
+    pub sauber_beendet: bool,
+    /// Pruefsumme der beim Start geladenen Konfiguration
+    pub config_hash: String,
 }
 
 /// Kanal-Informationen
@@ -200,6 +567,192 @@ pub struct KanalInfo {
     pub aktuelle_clients: u32,
     pub sort_order: i64,
     pub passwort_geschuetzt: bool,
+    /// Langsam-Modus in Sekunden (0 = aus)
+    #[serde(default)]
+    pub langsammodus_sek: i64,
+    /// Bearbeitungsfrist in Sekunden (None = Server-Standard, 0 = unbegrenzt)
+    #[serde(default)]
+    pub bearbeitungsfrist_sek: Option<i64>,
+}
+
+/// Kanal-Vorlagen-Informationen
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VorlageInfo {
+    pub id: Uuid,
+    pub name: String,
+    pub beschreibung: Option<String>,
+    /// Mitgelieferte Vorlage (nicht loeschbar)
+    pub eingebaut: bool,
+    pub anzahl_kanaele: usize,
+    pub tiefe: u32,
+    /// JSON-Definition des Teilbaums
+    pub definition: serde_json::Value,
+}
+
+/// Auftrag fuer einen Sammel-Move an den Signaling-Dienst
+#[derive(Debug, Clone, PartialEq)]
+pub struct SammelVerschiebung {
+    /// Ausloesender Benutzer (Berechtigungen und Move-Power werden fuer ihn geprueft)
+    pub aktor_id: Uuid,
+    pub von_kanal_id: Uuid,
+    pub nach_kanal_id: Uuid,
+    pub nur_user_ids: Option<Vec<Uuid>>,
+    pub teilweise: bool,
+}
+
+/// Ergebnis eines Sammel-Moves
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SammelVerschiebungErgebnis {
+    pub verschoben: Vec<Uuid>,
+    pub uebersprungen: Vec<UebersprungenerClient>,
+}
+
+/// Auftrag fuer die Notfall-Stummschaltung an den Signaling-Dienst
+#[derive(Debug, Clone, PartialEq)]
+pub struct NotfallStummAuftrag {
+    /// Ausloesender Benutzer (braucht `b_channel_emergency_mute` im Kanal)
+    pub aktor_id: Uuid,
+    pub kanal_id: Uuid,
+    pub aktivieren: bool,
+}
+
+/// Stand der Notfall-Stummschaltung nach dem Befehl
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NotfallStummErgebnis {
+    pub kanal_id: Uuid,
+    pub aktiv: bool,
+    /// Mitglieder, die weiter senden duerfen
+    pub ausgenommen: Vec<Uuid>,
+}
+
+/// Auftrag fuer Datenexport oder Loeschung eines Kontos an den Server
+#[derive(Debug, Clone, PartialEq)]
+pub struct KontoAuftrag {
+    /// Ausloesender Administrator
+    pub aktor_id: Uuid,
+    pub benutzer_id: Uuid,
+}
+
+/// Fertiger Datenexport (Einmal-Link, verfaellt nach dem ersten Abruf)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KontoExportErgebnis {
+    pub export_id: Uuid,
+    pub download_url: String,
+    pub laeuft_ab: chrono::DateTime<chrono::Utc>,
+    pub groesse_bytes: u64,
+}
+
+/// Ergebnis einer Kontoloeschung
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KontoLoeschErgebnis {
+    pub benutzer_id: Uuid,
+    /// Anonymisierte Nachrichten
+    pub nachrichten: u64,
+    pub dateien: u64,
+    /// Entfernte Berechtigungen und Gruppenmitgliedschaften
+    pub berechtigungen: u64,
+    pub sessions: u64,
+    pub api_tokens: u64,
+}
+
+/// Registrierter Benutzer fuer die Benutzerverwaltung
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BenutzerInfo {
+    pub id: Uuid,
+    pub username: String,
+    pub erstellt_am: chrono::DateTime<chrono::Utc>,
+    pub letzter_login: Option<chrono::DateTime<chrono::Utc>>,
+    /// `false` = gesperrt oder entfernt
+    pub aktiv: bool,
+    /// `false` = Standard- oder zurueckgesetztes Passwort noch aktiv
+    pub passwort_geaendert: bool,
+    /// Hat `b_server_modify` auf Server-Ebene ausdruecklich gewaehrt
+    pub administrator: bool,
+}
+
+/// Paginierte Antwort auf eine Benutzerliste
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BenutzerSeite {
+    pub benutzer: Vec<BenutzerInfo>,
+    /// Gesamtanzahl passender Benutzer (ohne Paginierung)
+    pub gesamt: u64,
+    pub limit: u32,
+    pub offset: u32,
+}
+
+/// Auftrag fuer eine Inhaltsbereinigung an den Signaling-Dienst
+#[derive(Debug, Clone, PartialEq)]
+pub struct BereinigungsAuftrag {
+    /// Ausloesender Moderator
+    pub aktor_id: Uuid,
+    pub benutzer_id: Uuid,
+    pub zeitraum: Duration,
+    pub nur_kanal: Option<Uuid>,
+    /// Abgebrochenen Auftrag fortsetzen
+    pub job_id: Option<Uuid>,
+}
+
+/// In einem Kanal entfernte Inhalte
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KanalBereinigungInfo {
+    pub kanal_id: Uuid,
+    pub nachrichten: u64,
+    pub dateien: u64,
+}
+
+/// Ergebnis einer Inhaltsbereinigung
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BereinigungsErgebnis {
+    /// Auftrags-ID, mit der ein abgebrochener Lauf fortgesetzt wird
+    pub job_id: Uuid,
+    pub benutzer_id: Uuid,
+    /// Insgesamt entfernte Nachrichten (auch aus frueheren Laeufen)
+    pub nachrichten: u64,
+    /// Insgesamt entfernte Dateien (auch aus frueheren Laeufen)
+    pub dateien: u64,
+    /// In diesem Lauf entfernt, pro Kanal
+    pub kanaele: Vec<KanalBereinigungInfo>,
+}
+
+/// Fuer das Soundboard kodierte Datei
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KodierterSound {
+    /// Opus-Frames in Abspielreihenfolge
+    pub frames: Vec<Vec<u8>>,
+    /// Dauer eines Frames in Millisekunden
+    pub frame_ms: u32,
+}
+
+/// Beim Sammel-Move uebersprungener Client
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UebersprungenerClient {
+    pub user_id: Uuid,
+    /// `not_in_channel`, `insufficient_power` oder `channel_full`
+    pub grund: String,
+}
+
+/// Ereignisse des Commanders, die an verbundene Clients weitergereicht werden
+#[derive(Debug, Clone, PartialEq)]
+pub enum CommanderEreignis {
+    /// Ein Kanal-Teilbaum wurde angelegt
+    KanalbaumGeaendert {
+        wurzel_id: Uuid,
+        parent_id: Option<Uuid>,
+        kanaele: Vec<Uuid>,
+    },
+    /// Die AFK-Richtlinie wurde geaendert (`None` = unveraendert)
+    AfkRichtlinieGeaendert {
+        timeout_sek: Option<u32>,
+        kanal_id: Option<Uuid>,
+    },
+    /// Server-Einstellungen wurden gespeichert (neuer Stand)
+    ServerEinstellungenGeaendert(ServerEinstellungen),
+    /// Fortschritt einer laufenden Sicherung
+    Sicherung(SicherungsFortschritt),
+    /// Die Nachricht des Tages wurde geaendert (neuer Stand)
+    MotdGeaendert(Motd),
+    /// Eine Alarmregel hat ausgeloest, ist behoben oder flattert
+    Alarm(AlarmMeldung),
 }
 
 /// Client-Informationen (ephemer)
@@ -214,6 +767,24 @@ pub struct ClientInfo {
     pub ip_adresse: Option<String>,
 }
 
+/// Voice-Diagnose eines Clients (Momentaufnahme des Voice-Servers)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VoiceDiagnoseInfo {
+    pub user_id: Uuid,
+    pub ssrc: u32,
+    pub kanal_id: Option<Uuid>,
+    /// Letzte gemessene Round-Trip-Time (ms)
+    pub rtt_ms: u32,
+    /// Verlust Client -> Server (0.0–1.0)
+    pub uplink_verlust: f64,
+    /// Vom Client gemeldeter Verlust Server -> Client (0.0–1.0)
+    pub downlink_verlust: f64,
+    /// Aktuelle Bitrate-Empfehlung (kbps)
+    pub bitrate_kbps: u16,
+    /// Geschaetzte Sprachqualitaet (1.0–4.5, `None` = noch kein Statistik-Bericht)
+    pub mos: Option<f64>,
+}
+
 /// Berechtigungs-Eintrag
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BerechtigungsEintrag {
@@ -221,6 +792,28 @@ pub struct BerechtigungsEintrag {
     pub wert: BerechtigungsWertInput,
 }
 
+/// Effektiver Berechtigungswert mit der Regel, die entschieden hat
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EffektiverBerechtigungsEintrag {
+    pub permission: String,
+    /// `None` = auf keiner Ebene gesetzt (Standard: erlaubt)
+    pub wert: Option<BerechtigungsWertInput>,
+    /// Entscheidende Stufe, z.B. `KanalGruppe` oder `ServerGruppe(Moderator)`
+    pub quelle: Option<String>,
+    /// `kanal` oder `server`
+    pub geltungsbereich: Option<String>,
+    /// Alle betrachteten Regeln in Pruefreihenfolge (inkl. Skip und ueberstimmter)
+    pub regeln: Vec<BerechtigungsRegel>,
+}
+
+/// Eine Regel aus der Aufloesung eines Keys
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BerechtigungsRegel {
+    pub quelle: String,
+    pub geltungsbereich: String,
+    pub wert: BerechtigungsWertInput,
+}
+
 /// Datei-Eintrag
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DateiEintrag {
@@ -231,6 +824,133 @@ pub struct DateiEintrag {
     pub hochgeladen_von: Uuid,
     pub hochgeladen_am_ms: u64,
     pub mime_typ: String,
+    /// Anzahl protokollierter Downloads
+    #[serde(default)]
+    pub zugriffe: u64,
+}
+
+/// Eintrag im Datei-Zugriffsprotokoll
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DateiZugriffEintrag {
+    pub id: Uuid,
+    pub datei_id: Uuid,
+    /// `None` bei Protokollierung im Aggregat-Modus
+    pub benutzer_id: Option<Uuid>,
+    pub zeitstempel: chrono::DateTime<chrono::Utc>,
+    pub bytes_ausgeliefert: u64,
+    pub vollstaendig: bool,
+}
+
+/// Paginierte Antwort auf eine Zugriffsprotokoll-Abfrage
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DateiZugriffSeite {
+    pub eintraege: Vec<DateiZugriffEintrag>,
+    /// Gesamtanzahl passender Eintraege (ohne Paginierung)
+    pub gesamt: u64,
+    pub limit: u32,
+    pub offset: u32,
+}
+
+/// Standardgroesse einer Listenseite
+pub const STANDARD_SEITENGROESSE: u32 = 50;
+
+/// Groesste Listenseite, die ausgeliefert wird
+pub const MAX_SEITENGROESSE: u32 = 1000;
+
+/// Angeforderte Seite einer Liste (Seitennummer ab 1)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SeitenAnfrage {
+    pub seite: u32,
+    pub groesse: u32,
+}
+
+impl SeitenAnfrage {
+    /// Fehlende oder 0-Werte nehmen den Standard an (Seite 1, 50 Eintraege),
+    /// die Groesse wird auf [`MAX_SEITENGROESSE`] begrenzt
+    pub fn neu(seite: Option<u32>, groesse: Option<u32>) -> Self {
+        Self {
+            seite: seite.filter(|s| *s > 0).unwrap_or(1),
+            groesse: groesse
+                .filter(|g| *g > 0)
+                .unwrap_or(STANDARD_SEITENGROESSE)
+                .min(MAX_SEITENGROESSE),
+        }
+    }
+
+    /// Anzahl der Eintraege vor dieser Seite
+    pub fn offset(&self) -> u64 {
+        u64::from(self.seite.saturating_sub(1)) * u64::from(self.groesse)
+    }
+
+    /// Seitenangaben fuer die Antwort bei `gesamt` passenden Eintraegen
+    pub fn info(&self, gesamt: u64) -> SeitenInfo {
+        SeitenInfo {
+            total_count: gesamt,
+            page: self.seite,
+            page_size: self.groesse,
+            has_next_page: self.offset() + u64::from(self.groesse) < gesamt,
+        }
+    }
+}
+
+impl Default for SeitenAnfrage {
+    fn default() -> Self {
+        Self::neu(None, None)
+    }
+}
+
+/// Seitenangaben einer paginierten Liste (Felder wie `PageInfo` im gRPC-Schema)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SeitenInfo {
+    /// Anzahl passender Eintraege ueber alle Seiten
+    pub total_count: u64,
+    pub page: u32,
+    pub page_size: u32,
+    pub has_next_page: bool,
+}
+
+/// Seite der Kanalliste
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KanalSeite {
+    pub kanaele: Vec<KanalInfo>,
+    #[serde(flatten)]
+    pub seite: SeitenInfo,
+}
+
+/// Seite der Client-Liste
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClientSeite {
+    pub clients: Vec<ClientInfo>,
+    #[serde(flatten)]
+    pub seite: SeitenInfo,
+}
+
+/// Chat-Nachricht fuer Moderatoren (z.B. bei Missbrauchsverdacht)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NachrichtInfo {
+    pub id: Uuid,
+    pub kanal_id: Uuid,
+    pub sender_id: Uuid,
+    pub inhalt: String,
+    pub erstellt_am: chrono::DateTime<chrono::Utc>,
+    pub bearbeitet_am: Option<chrono::DateTime<chrono::Utc>>,
+    pub geloescht_am: Option<chrono::DateTime<chrono::Utc>>,
+    /// Wie oft der Inhalt bearbeitet wurde
+    pub bearbeitungen: i64,
+}
+
+/// Soundboard-Sound
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SoundInfo {
+    pub id: Uuid,
+    pub name: String,
+    /// `None` = serverweit abspielbar
+    pub kanal_id: Option<Uuid>,
+    /// Hochgeladene Quelldatei
+    pub datei_id: Option<Uuid>,
+    pub dauer_ms: u32,
+    pub erstellt_von: Option<Uuid>,
+    pub erstellt_am: chrono::DateTime<chrono::Utc>,
 }
 
 /// Log-Eintrag
@@ -245,6 +965,25 @@ pub struct LogEintrag {
     pub details: serde_json::Value,
 }
 
+/// Geplante Aktion
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ZeitplanInfo {
+    pub id: Uuid,
+    pub name: String,
+    pub aktion: GeplanteAktion,
+    /// Zeitpunkt einer einmaligen Aktion (UTC)
+    pub einmalig: Option<chrono::DateTime<chrono::Utc>>,
+    /// Cron-Ausdruck einer wiederkehrenden Aktion
+    pub cron: Option<String>,
+    pub zeitzone: String,
+    /// `None` = erledigt oder abgebrochen
+    pub naechste_ausfuehrung: Option<chrono::DateTime<chrono::Utc>>,
+    pub letzte_ausfuehrung: Option<chrono::DateTime<chrono::Utc>>,
+    pub erstellt_von: Uuid,
+    pub abgebrochen: bool,
+    pub erstellt_am: chrono::DateTime<chrono::Utc>,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -263,6 +1002,9 @@ mod tests {
             willkommensnachricht: None,
             max_clients: Some(50),
             host_nachricht: None,
+            afk_timeout_sek: Some(600),
+            afk_kanal_id: None,
+            chat_bearbeitungsfrist_sek: Some(900),
         };
         assert!(matches!(cmd, Command::ServerEdit { .. }));
     }
@@ -292,12 +1034,32 @@ mod tests {
             aktuelle_clients: 5,
             version: "0.1.0".into(),
             uptime_secs: 3600,
+            host_nachricht: None,
+            features: vec!["grpc".into()],
+            letzter_shutdown_unsauber: false,
         });
         let json = serde_json::to_string(&resp).expect("Serialisierung fehlgeschlagen");
         assert!(json.contains("Test"));
         assert!(json.contains("3600"));
     }
 
+    #[test]
+    fn rest_nutzlast_ohne_typ_markierung() {
+        assert_eq!(
+            Response::Kanalbaum(vec![]).nutzlast().unwrap(),
+            serde_json::json!([])
+        );
+        assert_eq!(Response::Ok.nutzlast().unwrap(), serde_json::Value::Null);
+        assert_eq!(
+            Response::BackupGestartet {
+                ziel_pfad: "/sicherung".into()
+            }
+            .nutzlast()
+            .unwrap(),
+            serde_json::json!({ "ziel_pfad": "/sicherung" })
+        );
+    }
+
     #[test]
     fn berechtigung_wert_serialisierung() {
         let wert = BerechtigungsWertInput::Grant;
@@ -305,6 +1067,199 @@ mod tests {
         assert!(json.contains("Grant"));
     }
 
+    #[test]
+    fn seiten_anfrage_standard_und_grenzen() {
+        assert_eq!(
+            SeitenAnfrage::neu(None, None),
+            SeitenAnfrage {
+                seite: 1,
+                groesse: STANDARD_SEITENGROESSE
+            }
+        );
+        // 0 gilt als nicht angegeben (proto3-Standardwert)
+        assert_eq!(
+            SeitenAnfrage::neu(Some(0), Some(0)),
+            SeitenAnfrage::default()
+        );
+        assert_eq!(
+            SeitenAnfrage::neu(Some(2), Some(50_000)).groesse,
+            MAX_SEITENGROESSE
+        );
+        assert_eq!(SeitenAnfrage::neu(Some(3), Some(20)).offset(), 40);
+    }
+
+    #[test]
+    fn seiten_info_grenzen() {
+        let seite = SeitenAnfrage::neu(Some(2), Some(10));
+        // Genau zwei volle Seiten: keine weitere
+        assert!(!seite.info(20).has_next_page);
+        assert!(seite.info(21).has_next_page);
+        let info = SeitenAnfrage::neu(Some(9), Some(10)).info(21);
+        assert_eq!(info.total_count, 21);
+        assert!(!info.has_next_page);
+    }
+
+    #[test]
+    fn kanal_seite_nutzlast_ist_flach() {
+        let resp = Response::KanalListe(KanalSeite {
+            kanaele: vec![],
+            seite: SeitenAnfrage::default().info(0),
+        });
+        assert_eq!(
+            resp.nutzlast().unwrap(),
+            serde_json::json!({
+                "kanaele": [],
+                "total_count": 0,
+                "page": 1,
+                "page_size": 50,
+                "has_next_page": false
+            })
+        );
+    }
+
+    #[test]
+    fn datei_zugriffe_scope() {
+        let cmd = Command::DateiZugriffe {
+            datei_id: Some(Uuid::new_v4()),
+            benutzer_id: None,
+            von: None,
+            bis: None,
+            limit: 50,
+            offset: 0,
+        };
+        assert_eq!(cmd.erforderlicher_scope(), "cmd:fileaccesslog");
+    }
+
+    #[test]
+    fn nachricht_info_ist_lesend() {
+        let cmd = Command::NachrichtInfo {
+            nachricht_id: Uuid::new_v4(),
+        };
+        assert_eq!(cmd.erforderlicher_scope(), "cmd:messageinfo");
+        assert_eq!(cmd.zugriffsart(), Zugriffsart::Lesen);
+        assert!(!cmd.ist_teure_operation());
+    }
+
+    #[test]
+    fn vorlagen_scopes() {
+        assert_eq!(
+            Command::VorlageListe.erforderlicher_scope(),
+            "cmd:templatelist"
+        );
+        let cmd = Command::KanalbaumAusVorlage {
+            vorlage_id: Uuid::new_v4(),
+            parent_id: None,
+            name_prefix: None,
+        };
+        assert_eq!(cmd.erforderlicher_scope(), "cmd:channelcreate");
+        assert!(cmd.ist_teure_operation());
+    }
+
+    #[test]
+    fn berechtigung_effektiv_ist_lesend() {
+        let cmd = Command::BerechtigungEffektiv {
+            user_id: Uuid::new_v4(),
+            kanal_id: None,
+        };
+        assert_eq!(cmd.erforderlicher_scope(), "cmd:permissionlist");
+        assert_eq!(cmd.zugriffsart(), Zugriffsart::Lesen);
+        assert!(!cmd.ist_teure_operation());
+    }
+
+    #[test]
+    fn zugriffsart_lesen_und_schreiben() {
+        assert_eq!(
+            Command::KanalListe {
+                name: None,
+                seite: SeitenAnfrage::default(),
+            }
+            .zugriffsart(),
+            Zugriffsart::Lesen
+        );
+        assert_eq!(
+            Command::LogAbfragen {
+                limit: 50,
+                offset: 0,
+                aktion_filter: None,
+            }
+            .zugriffsart(),
+            Zugriffsart::Lesen
+        );
+        assert_eq!(
+            Command::KanalLoeschen { id: Uuid::new_v4() }.zugriffsart(),
+            Zugriffsart::Schreiben
+        );
+    }
+
+    #[test]
+    fn notfall_stumm_ist_schreibend() {
+        let cmd = Command::KanalNotfallStumm {
+            kanal_id: Uuid::new_v4(),
+            aktivieren: true,
+        };
+        assert_eq!(cmd.erforderlicher_scope(), "cmd:channelmute");
+        assert_eq!(cmd.zugriffsart(), Zugriffsart::Schreiben);
+    }
+
+    #[test]
+    fn zeitplan_befehle_brauchen_admin_scope() {
+        let cmd = Command::ZeitplanAbbrechen { id: Uuid::new_v4() };
+        assert_eq!(cmd.erforderlicher_scope(), "admin:schedule");
+        assert_eq!(cmd.zugriffsart(), Zugriffsart::Schreiben);
+        assert_eq!(Command::ZeitplanListe.zugriffsart(), Zugriffsart::Lesen);
+    }
+
+    #[test]
+    fn backup_braucht_admin_scope() {
+        let cmd = Command::BackupErstellen {
+            ziel_pfad: "/var/backups/speakeasy".into(),
+            include_files: true,
+        };
+        assert_eq!(cmd.erforderlicher_scope(), "admin:backup");
+        assert_eq!(cmd.zugriffsart(), Zugriffsart::Schreiben);
+        assert!(cmd.ist_teure_operation());
+    }
+
+    #[test]
+    fn soundboard_scopes() {
+        let liste = Command::SoundboardListe { kanal_id: None };
+        assert_eq!(liste.erforderlicher_scope(), "cmd:soundboardlist");
+        assert_eq!(liste.zugriffsart(), Zugriffsart::Lesen);
+        // Registrieren kodiert die Datei und gilt daher als teuer
+        let registrieren = Command::SoundboardRegistrieren {
+            name: "Gong".into(),
+            datei_id: Uuid::new_v4(),
+            kanal_id: None,
+        };
+        assert_eq!(registrieren.erforderlicher_scope(), "cmd:soundboardwrite");
+        assert!(registrieren.ist_teure_operation());
+        let entfernen = Command::SoundboardEntfernen { id: Uuid::new_v4() };
+        assert_eq!(entfernen.zugriffsart(), Zugriffsart::Schreiben);
+    }
+
+    #[test]
+    fn benutzerverwaltung_scopes() {
+        let liste = Command::BenutzerListe {
+            filter: None,
+            limit: 50,
+            offset: 0,
+        };
+        assert_eq!(liste.erforderlicher_scope(), "admin:users:read");
+        assert_eq!(liste.zugriffsart(), Zugriffsart::Lesen);
+        // Passwort-Hashing gilt als teuer
+        let passwort = Command::BenutzerPasswortSetzen {
+            user_id: Uuid::new_v4(),
+            neues_passwort: "geheim123".into(),
+        };
+        assert_eq!(passwort.erforderlicher_scope(), "admin:users:write");
+        assert!(passwort.ist_teure_operation());
+        let loeschen = Command::BenutzerLoeschen {
+            user_id: Uuid::new_v4(),
+        };
+        assert_eq!(loeschen.erforderlicher_scope(), "admin:users:write");
+        assert_eq!(loeschen.zugriffsart(), Zugriffsart::Schreiben);
+    }
+
     #[test]
     fn log_eintrag_felder() {
         let eintrag = LogEintrag {
@@ -1,5 +1,6 @@
 //! Fehlertypen fuer den Speakeasy Commander
 
+use speakeasy_core::SpeakeasyError;
 use thiserror::Error;
 
 /// Alle moeglichen Fehler im Commander-Crate
@@ -37,6 +38,9 @@ pub enum CommanderError {
 
     #[error("Protokollfehler: {0}")]
     Protokoll(String),
+
+    #[error("{0}")]
+    Zeitueberschreitung(#[from] speakeasy_db::zeitlimit::Zeitueberschreitung),
 }
 
 pub type CommanderResult<T> = Result<T, CommanderError>;
@@ -56,21 +60,74 @@ impl CommanderError {
             Self::Io(_) => 5001,
             Self::Tls(_) => 5002,
             Self::Protokoll(_) => 5003,
+            Self::Zeitueberschreitung(_) => 5004,
         }
     }
 }
 
-/// HTTP-Statuscode fuer REST-Fehler
-impl CommanderError {
-    pub fn http_status(&self) -> u16 {
-        match self {
-            Self::Authentifizierung(_) => 401,
-            Self::NichtAutorisiert(_) => 403,
-            Self::RateLimitUeberschritten { .. } => 429,
-            Self::NichtGefunden(_) => 404,
-            Self::UngueltigeEingabe(_) => 400,
-            Self::Datenbank(_) | Self::Auth(_) => 500,
-            Self::Intern(_) | Self::Io(_) | Self::Tls(_) | Self::Protokoll(_) => 500,
+impl From<CommanderError> for SpeakeasyError {
+    fn from(e: CommanderError) -> Self {
+        match e {
+            CommanderError::Authentifizierung(grund) => Self::Authentifizierung(grund),
+            CommanderError::NichtAutorisiert(grund) => Self::ZugriffVerweigert(grund),
+            CommanderError::RateLimitUeberschritten { retry_after_secs } => {
+                Self::RateLimit { retry_after_secs }
+            }
+            CommanderError::NichtGefunden(was) => Self::NichtGefunden(was),
+            CommanderError::UngueltigeEingabe(grund) => Self::UngueltigeEingabe(grund),
+            CommanderError::Datenbank(db) => db.into(),
+            CommanderError::Auth(auth) => auth.into(),
+            CommanderError::Intern(e) => Self::Anyhow(e),
+            CommanderError::Io(io) => io.into(),
+            CommanderError::Tls(grund) => Self::Verbindung(format!("TLS: {grund}")),
+            CommanderError::Protokoll(grund) => Self::UngueltigeNachricht(grund),
+            CommanderError::Zeitueberschreitung(z) => z.into(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use speakeasy_core::FehlerCode;
+
+    #[test]
+    fn jede_variante_hat_einen_fachlichen_code() {
+        let varianten = vec![
+            CommanderError::Authentifizierung("x".into()),
+            CommanderError::NichtAutorisiert("x".into()),
+            CommanderError::RateLimitUeberschritten {
+                retry_after_secs: 3,
+            },
+            CommanderError::NichtGefunden("x".into()),
+            CommanderError::UngueltigeEingabe("x".into()),
+            CommanderError::Datenbank(speakeasy_db::DbError::nicht_gefunden("x")),
+            CommanderError::Auth(speakeasy_auth::AuthError::SessionAbgelaufen),
+            CommanderError::Io(std::io::Error::new(
+                std::io::ErrorKind::ConnectionRefused,
+                "x",
+            )),
+            CommanderError::Tls("x".into()),
+            CommanderError::Protokoll("x".into()),
+        ];
+        for e in varianten {
+            let anzeige = e.to_string();
+            assert_ne!(
+                SpeakeasyError::from(e).code(),
+                FehlerCode::Intern,
+                "{anzeige}"
+            );
         }
+        let intern = CommanderError::Intern(anyhow::anyhow!("x"));
+        assert_eq!(SpeakeasyError::from(intern).code(), FehlerCode::Intern);
+    }
+
+    #[test]
+    fn rate_limit_behaelt_wartezeit() {
+        let fehler = SpeakeasyError::from(CommanderError::RateLimitUeberschritten {
+            retry_after_secs: 7,
+        });
+        assert_eq!(fehler.code().http_status(), 429);
+        assert_eq!(fehler.details()["retry_after_secs"], 7);
     }
 }
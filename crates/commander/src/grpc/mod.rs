@@ -3,4 +3,4 @@
 pub mod server;
 pub mod services;
 
-pub use server::{GrpcServer, GrpcServerKonfig};
+pub use server::{GrpcServer, GrpcServerKonfig, GrpcVerbindungsInfo};
@@ -1,35 +1,119 @@
 //! gRPC-Server fuer den Speakeasy Commander
+//!
+//! Mit [`TlsKonfig`] nimmt der Server die Verbindungen selbst an (wie der
+//! REST-Server); das Client-Zertifikat steht dann in den Request-Extensions
+//! als [`GrpcVerbindungsInfo`].
+//!
+//! Nach dem Shutdown-Signal nimmt der Server keine Verbindungen mehr an und
+//! beendet sich, sobald die laufenden Aufrufe abgeschlossen sind.
 
+use std::io;
 use std::net::SocketAddr;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
 
 use anyhow::Result;
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::{mpsc, watch};
+use tokio_rustls::server::TlsStream;
+use tokio_stream::wrappers::ReceiverStream;
+use tokio_stream::StreamExt;
+use tonic::transport::server::Connected;
 use tonic::transport::Server;
 
 use crate::grpc::services::{
     proto::{
         channel_service_server::ChannelServiceServer, client_service_server::ClientServiceServer,
         file_service_server::FileServiceServer, permission_service_server::PermissionServiceServer,
-        server_service_server::ServerServiceServer,
+        server_service_server::ServerServiceServer, user_service_server::UserServiceServer,
     },
     ChannelServiceImpl, ClientServiceImpl, FileServiceImpl, PermissionServiceImpl,
-    ServerServiceImpl,
+    ServerServiceImpl, UserServiceImpl,
 };
 use crate::rest::CommanderState;
+use crate::tls::{self, ClientIdentitaet, TlsKonfig, TlsQuelle, TlsVerbindung};
 
 /// gRPC-Server-Konfiguration
 #[derive(Debug, Clone)]
 pub struct GrpcServerKonfig {
     pub bind_addr: SocketAddr,
+    /// TLS (optional mit Client-Zertifikaten); `None` = unverschluesselt
+    pub tls: Option<TlsKonfig>,
 }
 
 impl Default for GrpcServerKonfig {
     fn default() -> Self {
         Self {
             bind_addr: "127.0.0.1:9302".parse().unwrap(),
+            tls: None,
         }
     }
 }
 
+/// Verbindungsdaten einer TLS-Verbindung (in den Request-Extensions)
+#[derive(Debug, Clone)]
+pub struct GrpcVerbindungsInfo {
+    pub remote_addr: SocketAddr,
+    /// Verifiziertes Client-Zertifikat
+    pub identitaet: Option<ClientIdentitaet>,
+}
+
+/// TLS-Stream, der tonic seine [`GrpcVerbindungsInfo`] mitgibt
+struct GrpcTlsStream {
+    inner: TlsStream<TcpStream>,
+    info: GrpcVerbindungsInfo,
+}
+
+impl From<TlsVerbindung> for GrpcTlsStream {
+    fn from(verbindung: TlsVerbindung) -> Self {
+        Self {
+            inner: verbindung.stream,
+            info: GrpcVerbindungsInfo {
+                remote_addr: verbindung.peer,
+                identitaet: verbindung.identitaet,
+            },
+        }
+    }
+}
+
+impl Connected for GrpcTlsStream {
+    type ConnectInfo = GrpcVerbindungsInfo;
+
+    fn connect_info(&self) -> Self::ConnectInfo {
+        self.info.clone()
+    }
+}
+
+impl AsyncRead for GrpcTlsStream {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.inner).poll_read(cx, buf)
+    }
+}
+
+impl AsyncWrite for GrpcTlsStream {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.inner).poll_write(cx, buf)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.inner).poll_shutdown(cx)
+    }
+}
+
 /// gRPC-Commander-Server
 pub struct GrpcServer {
     konfig: GrpcServerKonfig,
@@ -41,10 +125,26 @@ impl GrpcServer {
     }
 
     /// Startet den gRPC-Server mit dem gegebenen CommanderState
-    pub async fn starten(self, state: CommanderState) -> Result<()> {
-        tracing::info!(addr = %self.konfig.bind_addr, "gRPC-Commander-Server gestartet");
+    ///
+    /// Kehrt zurueck, sobald `shutdown_rx` `true` meldet (oder der Sender
+    /// verworfen wird) und alle laufenden Aufrufe beantwortet sind.
+    pub async fn starten(
+        self,
+        state: CommanderState,
+        mut shutdown_rx: watch::Receiver<bool>,
+    ) -> Result<()> {
+        let quelle = match self.konfig.tls {
+            Some(tls) => Some(Arc::new(TlsQuelle::laden(tls, &[b"h2"])?)),
+            None => None,
+        };
+        tracing::info!(
+            addr = %self.konfig.bind_addr,
+            tls = quelle.is_some(),
+            mtls = quelle.as_ref().is_some_and(|q| q.mit_client_zertifikaten()),
+            "gRPC-Commander-Server gestartet"
+        );
 
-        Server::builder()
+        let router = Server::builder()
             .add_service(ServerServiceServer::new(ServerServiceImpl::neu(
                 state.clone(),
             )))
@@ -57,10 +157,32 @@ impl GrpcServer {
             .add_service(PermissionServiceServer::new(PermissionServiceImpl::neu(
                 state.clone(),
             )))
-            .add_service(FileServiceServer::new(FileServiceImpl::neu(state)))
-            .serve(self.konfig.bind_addr)
-            .await?;
+            .add_service(FileServiceServer::new(FileServiceImpl::neu(state.clone())))
+            .add_service(UserServiceServer::new(UserServiceImpl::neu(state)));
+
+        let beendet = async move {
+            let _ = shutdown_rx.wait_for(|beenden| *beenden).await;
+        };
+
+        let Some(quelle) = quelle else {
+            router
+                .serve_with_shutdown(self.konfig.bind_addr, beendet)
+                .await?;
+            tracing::info!("gRPC-Commander-Server beendet");
+            return Ok(());
+        };
+
+        let listener = TcpListener::bind(self.konfig.bind_addr).await?;
+        let nachladen = tokio::spawn(Arc::clone(&quelle).beobachten());
+        let (tx, rx) = mpsc::channel(64);
+        let annahme = tokio::spawn(tls::annehmen(listener, quelle, tx));
+        let eingang = ReceiverStream::new(rx).map(|v| Ok::<_, io::Error>(GrpcTlsStream::from(v)));
 
+        let ergebnis = router.serve_with_incoming_shutdown(eingang, beendet).await;
+        nachladen.abort();
+        annahme.abort();
+        ergebnis?;
+        tracing::info!("gRPC-Commander-Server beendet");
         Ok(())
     }
 }
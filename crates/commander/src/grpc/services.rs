@@ -3,12 +3,14 @@
 //! Alle Services nutzen denselben type-erased ExecutorFn wie der REST-Server,
 //! um Send-Futures aus nicht-Send Repository-Traits zu vermeiden.
 
+use speakeasy_core::SpeakeasyError;
 use tonic::{Request, Response, Status};
 use uuid::Uuid;
 
-use crate::commands::types::{BerechtigungsWertInput, Command};
+use crate::commands::types::{BerechtigungsWertInput, Command, SeitenAnfrage, SeitenInfo};
 use crate::error::CommanderError;
-use crate::rest::{CommanderState, TokenValidatorFn};
+use crate::grpc::server::GrpcVerbindungsInfo;
+use crate::rest::CommanderState;
 
 // Generierter Code aus tonic-build
 pub mod proto {
@@ -18,20 +20,32 @@ pub mod proto {
 use proto::*;
 
 // ---------------------------------------------------------------------------
-// Hilfsfunktion: Token aus gRPC-Metadaten extrahieren
+// Hilfsfunktion: Session aus Token oder Client-Zertifikat
 // ---------------------------------------------------------------------------
 
-fn session_aus_metadata(
-    metadata: &tonic::metadata::MetadataMap,
-    token_validator: &TokenValidatorFn,
+/// Ohne Bearer-Token gilt das Client-Zertifikat der Verbindung, sofern
+/// Zertifikate den Token ersetzen duerfen.
+fn session_aus_anfrage<T>(
+    request: &Request<T>,
+    state: &CommanderState,
 ) -> Result<crate::auth::CommanderSession, Status> {
-    let token = metadata
+    let Some(token) = request
+        .metadata()
         .get("authorization")
         .and_then(|v| v.to_str().ok())
         .and_then(|s| s.strip_prefix("Bearer "))
-        .ok_or_else(|| Status::unauthenticated("Authorization-Metadaten fehlen"))?;
+    else {
+        let identitaet = request
+            .extensions()
+            .get::<GrpcVerbindungsInfo>()
+            .and_then(|info| info.identitaet.as_ref());
+        return match state.zertifikats_session(identitaet) {
+            Some(session) => session.map_err(|e| Status::unauthenticated(e.to_string())),
+            None => Err(Status::unauthenticated("Authorization-Metadaten fehlen")),
+        };
+    };
 
-    token_validator(token)
+    (state.token_validator)(token)
         .map_err(|_| Status::unauthenticated("Ungueltiger oder abgelaufener Token"))
 }
 
@@ -55,7 +69,7 @@ impl proto::server_service_server::ServerService for ServerServiceImpl {
         &self,
         request: Request<Empty>,
     ) -> Result<Response<ServerInfo>, Status> {
-        let session = session_aus_metadata(request.metadata(), &self.state.token_validator)?;
+        let session = session_aus_anfrage(&request, &self.state)?;
         match self.state.ausfuehren(Command::ServerInfo, session).await {
             Ok(crate::commands::types::Response::ServerInfo(info)) => {
                 Ok(Response::new(ServerInfo {
@@ -68,8 +82,9 @@ impl proto::server_service_server::ServerService for ServerServiceImpl {
                     current_clients: info.aktuelle_clients,
                     version: info.version,
                     uptime_secs: info.uptime_secs,
-                    host_message: String::new(),
+                    host_message: info.host_nachricht.unwrap_or_default(),
                     default_groups: vec![],
+                    features: info.features,
                 }))
             }
             Ok(_) => Err(Status::internal("Unerwarteter Response-Typ")),
@@ -81,13 +96,16 @@ impl proto::server_service_server::ServerService for ServerServiceImpl {
         &self,
         request: Request<UpdateServerRequest>,
     ) -> Result<Response<ServerInfo>, Status> {
-        let session = session_aus_metadata(request.metadata(), &self.state.token_validator)?;
+        let session = session_aus_anfrage(&request, &self.state)?;
         let body = request.into_inner();
         let cmd = Command::ServerEdit {
             name: Some(body.name).filter(|s| !s.is_empty()),
             willkommensnachricht: Some(body.welcome_message).filter(|s| !s.is_empty()),
             max_clients: Some(body.max_clients).filter(|&n| n > 0),
             host_nachricht: Some(body.host_message).filter(|s| !s.is_empty()),
+            afk_timeout_sek: None,
+            afk_kanal_id: None,
+            chat_bearbeitungsfrist_sek: None,
         };
         self.state
             .ausfuehren(cmd, session.clone())
@@ -105,8 +123,9 @@ impl proto::server_service_server::ServerService for ServerServiceImpl {
                     current_clients: info.aktuelle_clients,
                     version: info.version,
                     uptime_secs: info.uptime_secs,
-                    host_message: String::new(),
+                    host_message: info.host_nachricht.unwrap_or_default(),
                     default_groups: vec![],
+                    features: info.features,
                 }))
             }
             _ => Err(Status::internal("Konnte Server-Info nicht laden")),
@@ -117,7 +136,7 @@ impl proto::server_service_server::ServerService for ServerServiceImpl {
         &self,
         request: Request<StopServerRequest>,
     ) -> Result<Response<Empty>, Status> {
-        let session = session_aus_metadata(request.metadata(), &self.state.token_validator)?;
+        let session = session_aus_anfrage(&request, &self.state)?;
         let body = request.into_inner();
         self.state
             .ausfuehren(
@@ -167,20 +186,16 @@ impl proto::channel_service_server::ChannelService for ChannelServiceImpl {
         &self,
         request: Request<ListChannelsRequest>,
     ) -> Result<Response<ListChannelsResponse>, Status> {
-        let session = session_aus_metadata(request.metadata(), &self.state.token_validator)?;
-        match self.state.ausfuehren(Command::KanalListe, session).await {
-            Ok(crate::commands::types::Response::KanalListe(kanaele)) => {
-                let channels: Vec<ChannelInfo> =
-                    kanaele.into_iter().map(kanal_info_zu_proto).collect();
-                let total = channels.len() as u32;
+        let session = session_aus_anfrage(&request, &self.state)?;
+        let cmd = Command::KanalListe {
+            name: None,
+            seite: seiten_anfrage(request.get_ref().page.as_ref()),
+        };
+        match self.state.ausfuehren(cmd, session).await {
+            Ok(crate::commands::types::Response::KanalListe(seite)) => {
                 Ok(Response::new(ListChannelsResponse {
-                    channels,
-                    page_info: Some(PageInfo {
-                        total_count: total,
-                        page: 1,
-                        page_size: total,
-                        has_next_page: false,
-                    }),
+                    channels: seite.kanaele.into_iter().map(kanal_info_zu_proto).collect(),
+                    page_info: Some(seiten_info_zu_proto(seite.seite)),
                 }))
             }
             Ok(_) => Err(Status::internal("Unerwarteter Response-Typ")),
@@ -199,7 +214,7 @@ impl proto::channel_service_server::ChannelService for ChannelServiceImpl {
         &self,
         request: Request<CreateChannelRequest>,
     ) -> Result<Response<CreateChannelResponse>, Status> {
-        let session = session_aus_metadata(request.metadata(), &self.state.token_validator)?;
+        let session = session_aus_anfrage(&request, &self.state)?;
         let body = request.into_inner();
         let cmd = Command::KanalErstellen {
             name: body.name,
@@ -227,7 +242,7 @@ impl proto::channel_service_server::ChannelService for ChannelServiceImpl {
         &self,
         request: Request<UpdateChannelRequest>,
     ) -> Result<Response<ChannelInfo>, Status> {
-        let session = session_aus_metadata(request.metadata(), &self.state.token_validator)?;
+        let session = session_aus_anfrage(&request, &self.state)?;
         let body = request.into_inner();
         let id = body
             .channel_id
@@ -239,6 +254,8 @@ impl proto::channel_service_server::ChannelService for ChannelServiceImpl {
             thema: Some(body.description).filter(|s| !s.is_empty()).map(Some),
             max_clients: Some(body.max_clients as i64).filter(|&n| n > 0),
             sort_order: Some(body.sort_order as i64),
+            langsammodus_sek: None,
+            bearbeitungsfrist_sek: None,
         };
         match self.state.ausfuehren(cmd, session).await {
             Ok(crate::commands::types::Response::Kanal(kanal)) => {
@@ -253,7 +270,7 @@ impl proto::channel_service_server::ChannelService for ChannelServiceImpl {
         &self,
         request: Request<DeleteChannelRequest>,
     ) -> Result<Response<Empty>, Status> {
-        let session = session_aus_metadata(request.metadata(), &self.state.token_validator)?;
+        let session = session_aus_anfrage(&request, &self.state)?;
         let body = request.into_inner();
         let id = body
             .channel_id
@@ -287,20 +304,30 @@ impl proto::client_service_server::ClientService for ClientServiceImpl {
         &self,
         request: Request<ListClientsRequest>,
     ) -> Result<Response<ListClientsResponse>, Status> {
-        let session = session_aus_metadata(request.metadata(), &self.state.token_validator)?;
-        match self.state.ausfuehren(Command::ClientListe, session).await {
-            Ok(crate::commands::types::Response::ClientListe(clients)) => {
-                let proto_clients: Vec<ClientInfo> =
-                    clients.into_iter().map(client_info_zu_proto).collect();
-                let total = proto_clients.len() as u32;
+        let session = session_aus_anfrage(&request, &self.state)?;
+        let body = request.get_ref();
+        let kanal_id = body
+            .channel_id
+            .as_ref()
+            .map(|cid| {
+                Uuid::parse_str(&cid.value)
+                    .map_err(|_| Status::invalid_argument("Ungueltige channel_id"))
+            })
+            .transpose()?;
+        let cmd = Command::ClientListe {
+            kanal_id,
+            username: None,
+            seite: seiten_anfrage(body.page.as_ref()),
+        };
+        match self.state.ausfuehren(cmd, session).await {
+            Ok(crate::commands::types::Response::ClientListe(seite)) => {
                 Ok(Response::new(ListClientsResponse {
-                    clients: proto_clients,
-                    page_info: Some(PageInfo {
-                        total_count: total,
-                        page: 1,
-                        page_size: total,
-                        has_next_page: false,
-                    }),
+                    clients: seite
+                        .clients
+                        .into_iter()
+                        .map(client_info_zu_proto)
+                        .collect(),
+                    page_info: Some(seiten_info_zu_proto(seite.seite)),
                 }))
             }
             Ok(_) => Err(Status::internal("Unerwarteter Response-Typ")),
@@ -316,7 +343,7 @@ impl proto::client_service_server::ClientService for ClientServiceImpl {
         &self,
         request: Request<KickClientRequest>,
     ) -> Result<Response<Empty>, Status> {
-        let session = session_aus_metadata(request.metadata(), &self.state.token_validator)?;
+        let session = session_aus_anfrage(&request, &self.state)?;
         let body = request.into_inner();
         let client_id = body
             .target_user_id
@@ -339,7 +366,7 @@ impl proto::client_service_server::ClientService for ClientServiceImpl {
         &self,
         request: Request<BanClientRequest>,
     ) -> Result<Response<Empty>, Status> {
-        let session = session_aus_metadata(request.metadata(), &self.state.token_validator)?;
+        let session = session_aus_anfrage(&request, &self.state)?;
         let body = request.into_inner();
         let client_id = body
             .target_user_id
@@ -352,6 +379,7 @@ impl proto::client_service_server::ClientService for ClientServiceImpl {
                     dauer_secs: Some(body.duration_secs).filter(|&d| d > 0),
                     grund: Some(body.reason).filter(|s| !s.is_empty()),
                     ip_bannen: body.ban_ip,
+                    inhalte_entfernen: None,
                 },
                 session,
             )
@@ -364,7 +392,7 @@ impl proto::client_service_server::ClientService for ClientServiceImpl {
         &self,
         request: Request<MoveClientRequest>,
     ) -> Result<Response<Empty>, Status> {
-        let session = session_aus_metadata(request.metadata(), &self.state.token_validator)?;
+        let session = session_aus_anfrage(&request, &self.state)?;
         let body = request.into_inner();
         let client_id = body
             .target_user_id
@@ -408,7 +436,7 @@ impl proto::permission_service_server::PermissionService for PermissionServiceIm
         &self,
         request: Request<GetPermissionsRequest>,
     ) -> Result<Response<GetPermissionsResponse>, Status> {
-        let session = session_aus_metadata(request.metadata(), &self.state.token_validator)?;
+        let session = session_aus_anfrage(&request, &self.state)?;
         let body = request.into_inner();
         match self
             .state
@@ -443,7 +471,7 @@ impl proto::permission_service_server::PermissionService for PermissionServiceIm
         &self,
         request: Request<SetPermissionRequest>,
     ) -> Result<Response<Empty>, Status> {
-        let session = session_aus_metadata(request.metadata(), &self.state.token_validator)?;
+        let session = session_aus_anfrage(&request, &self.state)?;
         let body = request.into_inner();
         let wert = body
             .value
@@ -468,7 +496,7 @@ impl proto::permission_service_server::PermissionService for PermissionServiceIm
         &self,
         request: Request<RemovePermissionRequest>,
     ) -> Result<Response<Empty>, Status> {
-        let session = session_aus_metadata(request.metadata(), &self.state.token_validator)?;
+        let session = session_aus_anfrage(&request, &self.state)?;
         let body = request.into_inner();
         self.state
             .ausfuehren(
@@ -505,7 +533,7 @@ impl proto::file_service_server::FileService for FileServiceImpl {
         &self,
         request: Request<ListFilesRequest>,
     ) -> Result<Response<ListFilesResponse>, Status> {
-        let session = session_aus_metadata(request.metadata(), &self.state.token_validator)?;
+        let session = session_aus_anfrage(&request, &self.state)?;
         let body = request.into_inner();
         let kanal_id = body
             .channel_id
@@ -561,7 +589,7 @@ impl proto::file_service_server::FileService for FileServiceImpl {
         &self,
         request: Request<DeleteFileRequest>,
     ) -> Result<Response<Empty>, Status> {
-        let session = session_aus_metadata(request.metadata(), &self.state.token_validator)?;
+        let session = session_aus_anfrage(&request, &self.state)?;
         let body = request.into_inner();
         self.state
             .ausfuehren(
@@ -576,18 +604,130 @@ impl proto::file_service_server::FileService for FileServiceImpl {
     }
 }
 
+// ---------------------------------------------------------------------------
+// UserService
+// ---------------------------------------------------------------------------
+
+pub struct UserServiceImpl {
+    state: CommanderState,
+}
+
+impl UserServiceImpl {
+    pub fn neu(state: CommanderState) -> Self {
+        Self { state }
+    }
+}
+
+#[tonic::async_trait]
+impl proto::user_service_server::UserService for UserServiceImpl {
+    async fn list_users(
+        &self,
+        request: Request<ListUsersRequest>,
+    ) -> Result<Response<ListUsersResponse>, Status> {
+        let session = session_aus_anfrage(&request, &self.state)?;
+        let body = request.into_inner();
+        let cmd = Command::BenutzerListe {
+            filter: Some(body.filter).filter(|f| !f.is_empty()),
+            limit: Some(body.limit).filter(|&l| l > 0).unwrap_or(50).min(1000),
+            offset: body.offset,
+        };
+        match self.state.ausfuehren(cmd, session).await {
+            Ok(crate::commands::types::Response::BenutzerListe(seite)) => {
+                Ok(Response::new(ListUsersResponse {
+                    users: seite
+                        .benutzer
+                        .into_iter()
+                        .map(benutzer_info_zu_proto)
+                        .collect(),
+                    total_count: seite.gesamt,
+                }))
+            }
+            Ok(_) => Err(Status::internal("Unerwarteter Response-Typ")),
+            Err(e) => Err(commander_error_zu_status(e)),
+        }
+    }
+
+    async fn set_user_password(
+        &self,
+        request: Request<SetUserPasswordRequest>,
+    ) -> Result<Response<UserInfo>, Status> {
+        let session = session_aus_anfrage(&request, &self.state)?;
+        let body = request.into_inner();
+        let user_id = body
+            .user_id
+            .and_then(|uid| Uuid::parse_str(&uid.value).ok())
+            .ok_or_else(|| Status::invalid_argument("Ungueltige user_id"))?;
+        let cmd = Command::BenutzerPasswortSetzen {
+            user_id,
+            neues_passwort: body.new_password,
+        };
+        match self.state.ausfuehren(cmd, session).await {
+            Ok(crate::commands::types::Response::Benutzer(info)) => {
+                Ok(Response::new(benutzer_info_zu_proto(info)))
+            }
+            Ok(_) => Err(Status::internal("Unerwarteter Response-Typ")),
+            Err(e) => Err(commander_error_zu_status(e)),
+        }
+    }
+
+    async fn disable_user(
+        &self,
+        request: Request<DisableUserRequest>,
+    ) -> Result<Response<UserInfo>, Status> {
+        let session = session_aus_anfrage(&request, &self.state)?;
+        let body = request.into_inner();
+        let user_id = body
+            .user_id
+            .and_then(|uid| Uuid::parse_str(&uid.value).ok())
+            .ok_or_else(|| Status::invalid_argument("Ungueltige user_id"))?;
+        let cmd = Command::BenutzerDeaktivieren {
+            user_id,
+            deaktiviert: body.disabled,
+        };
+        match self.state.ausfuehren(cmd, session).await {
+            Ok(crate::commands::types::Response::Benutzer(info)) => {
+                Ok(Response::new(benutzer_info_zu_proto(info)))
+            }
+            Ok(_) => Err(Status::internal("Unerwarteter Response-Typ")),
+            Err(e) => Err(commander_error_zu_status(e)),
+        }
+    }
+
+    async fn delete_user(&self, request: Request<UserId>) -> Result<Response<Empty>, Status> {
+        let session = session_aus_anfrage(&request, &self.state)?;
+        let user_id = Uuid::parse_str(&request.get_ref().value)
+            .map_err(|_| Status::invalid_argument("Ungueltige user_id"))?;
+        self.state
+            .ausfuehren(Command::BenutzerLoeschen { user_id }, session)
+            .await
+            .map_err(commander_error_zu_status)?;
+        Ok(Response::new(Empty {}))
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Hilfsfunktionen
 // ---------------------------------------------------------------------------
 
 fn commander_error_zu_status(e: CommanderError) -> Status {
-    match e {
-        CommanderError::Authentifizierung(_) => Status::unauthenticated(e.to_string()),
-        CommanderError::NichtAutorisiert(_) => Status::permission_denied(e.to_string()),
-        CommanderError::NichtGefunden(_) => Status::not_found(e.to_string()),
-        CommanderError::UngueltigeEingabe(_) => Status::invalid_argument(e.to_string()),
-        CommanderError::RateLimitUeberschritten { .. } => Status::resource_exhausted(e.to_string()),
-        _ => Status::internal(e.to_string()),
+    let fehler = SpeakeasyError::from(e);
+    Status::new(
+        tonic::Code::from_i32(fehler.code().grpc_status()),
+        fehler.to_string(),
+    )
+}
+
+/// Seite aus dem `PageRequest` (fehlend oder 0 = Standardwerte wie bei REST)
+fn seiten_anfrage(page: Option<&PageRequest>) -> SeitenAnfrage {
+    SeitenAnfrage::neu(page.map(|p| p.page), page.map(|p| p.page_size))
+}
+
+fn seiten_info_zu_proto(info: SeitenInfo) -> PageInfo {
+    PageInfo {
+        total_count: u32::try_from(info.total_count).unwrap_or(u32::MAX),
+        page: info.page,
+        page_size: info.page_size,
+        has_next_page: info.has_next_page,
     }
 }
 
@@ -631,6 +771,23 @@ fn client_info_zu_proto(c: crate::commands::types::ClientInfo) -> ClientInfo {
     }
 }
 
+fn benutzer_info_zu_proto(b: crate::commands::types::BenutzerInfo) -> UserInfo {
+    UserInfo {
+        user_id: Some(UserId {
+            value: b.id.to_string(),
+        }),
+        username: b.username,
+        created_at_ms: u64::try_from(b.erstellt_am.timestamp_millis()).unwrap_or(0),
+        last_login_ms: b
+            .letzter_login
+            .and_then(|t| u64::try_from(t.timestamp_millis()).ok())
+            .unwrap_or(0),
+        is_active: b.aktiv,
+        password_changed: b.passwort_geaendert,
+        is_admin: b.administrator,
+    }
+}
+
 fn berechtigung_zu_proto(wert: BerechtigungsWertInput) -> PermissionValue {
     use proto::permission_value::Value;
     let value = match wert {
@@ -655,3 +812,160 @@ fn proto_zu_berechtigung(pv: PermissionValue) -> BerechtigungsWertInput {
 
 // Exportiert ExecutorFn und TokenValidatorFn fuer den gRPC-Server
 pub use crate::rest::{ExecutorFn as GrpcExecutorFn, TokenValidatorFn as GrpcTokenValidatorFn};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::{AuthArt, CommanderSession};
+    use crate::commands::CommandExecutor;
+    use crate::rest::{handlers, ExecutorFn, TokenValidatorFn};
+    use axum::{extract::State, http::HeaderMap};
+    use proto::server_service_server::ServerService;
+    use speakeasy_auth::{ApiTokenStore, AuthService, BanService, PermissionService, SessionStore};
+    use speakeasy_db::{
+        einstellungen::ServerEinstellungen,
+        models::{KanalbaumGrenzen, NeuerBenutzer},
+        SqliteDb, UserRepository,
+    };
+    use std::sync::Arc;
+
+    const TOKEN: &str = "test-token";
+
+    async fn state() -> CommanderState {
+        let db = Arc::new(SqliteDb::in_memory().await.unwrap());
+        let benutzer = UserRepository::create(
+            db.as_ref(),
+            NeuerBenutzer {
+                username: "admin",
+                password_hash: "hash",
+            },
+        )
+        .await
+        .unwrap();
+        let executor = CommandExecutor::neu(
+            Arc::clone(&db),
+            Arc::clone(&db),
+            Arc::clone(&db),
+            Arc::clone(&db),
+            Arc::clone(&db),
+            Arc::clone(&db),
+            Arc::clone(&db),
+            Arc::clone(&db),
+            Arc::clone(&db),
+            Arc::new(AuthService::neu(
+                Arc::clone(&db),
+                SessionStore::neu(),
+                ApiTokenStore::neu(),
+            )),
+            PermissionService::neu(Arc::clone(&db)),
+            BanService::neu(Arc::clone(&db)),
+            ServerEinstellungen {
+                name: "Test".into(),
+                willkommensnachricht: None,
+                max_clients: 32,
+                host_nachricht: None,
+                chat_bearbeitungsfrist_sek: 0,
+            },
+            "0.0.0".into(),
+            KanalbaumGrenzen::default(),
+        );
+        let executor_fn: ExecutorFn = Arc::new(move |cmd, session| {
+            let exec = Arc::clone(&executor);
+            Box::pin(async move { exec.ausfuehren(cmd, &session).await })
+        });
+        let validator: TokenValidatorFn = Arc::new(move |token| {
+            if token == TOKEN {
+                Ok(CommanderSession {
+                    benutzer: benutzer.clone(),
+                    scopes: vec![],
+                    auth_art: AuthArt::Session,
+                })
+            } else {
+                Err(CommanderError::Authentifizierung(
+                    "Unbekannter Token".into(),
+                ))
+            }
+        });
+        CommanderState::neu(executor_fn, validator)
+    }
+
+    #[tokio::test]
+    async fn aenderung_per_grpc_ist_per_rest_sichtbar() {
+        let state = state().await;
+        let service = ServerServiceImpl::neu(state.clone());
+
+        let mut anfrage = Request::new(UpdateServerRequest {
+            name: "Umbenannt".into(),
+            welcome_message: "Willkommen!".into(),
+            max_clients: 64,
+            host_message: "Wartung heute Abend".into(),
+        });
+        anfrage
+            .metadata_mut()
+            .insert("authorization", format!("Bearer {TOKEN}").parse().unwrap());
+        let info = service.update_server(anfrage).await.unwrap().into_inner();
+        assert_eq!(info.welcome_message, "Willkommen!");
+        assert_eq!(info.host_message, "Wartung heute Abend");
+
+        let mut headers = HeaderMap::new();
+        headers.insert("authorization", format!("Bearer {TOKEN}").parse().unwrap());
+        let antwort = handlers::server::get_server(State(state), headers).await;
+        assert_eq!(antwort.status(), axum::http::StatusCode::OK);
+
+        let body = axum::body::to_bytes(antwort.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(json["name"], "Umbenannt");
+        assert_eq!(json["willkommensnachricht"], "Willkommen!");
+        assert_eq!(json["max_clients"], 64);
+        assert_eq!(json["host_nachricht"], "Wartung heute Abend");
+    }
+
+    fn mit_token<T>(nachricht: T) -> Request<T> {
+        let mut anfrage = Request::new(nachricht);
+        anfrage
+            .metadata_mut()
+            .insert("authorization", format!("Bearer {TOKEN}").parse().unwrap());
+        anfrage
+    }
+
+    #[tokio::test]
+    async fn kanaele_per_grpc_seitenweise() {
+        use proto::channel_service_server::ChannelService;
+
+        let service = ChannelServiceImpl::neu(state().await);
+        for name in ["Eins", "Zwei", "Drei"] {
+            service
+                .create_channel(mit_token(CreateChannelRequest {
+                    name: name.into(),
+                    ..Default::default()
+                }))
+                .await
+                .unwrap();
+        }
+        let seite = |page, page_size| ListChannelsRequest {
+            server_id: None,
+            page: Some(PageRequest { page, page_size }),
+        };
+
+        let antwort = service
+            .list_channels(mit_token(seite(2, 2)))
+            .await
+            .unwrap()
+            .into_inner();
+        let info = antwort.page_info.unwrap();
+        assert_eq!(antwort.channels.len() as u32, info.total_count - 2);
+        assert_eq!((info.page, info.page_size), (2, 2));
+        assert!(!info.has_next_page);
+
+        // Jenseits des Endes: leer, Gesamtzahl bleibt korrekt
+        let antwort = service
+            .list_channels(mit_token(seite(50, 2)))
+            .await
+            .unwrap()
+            .into_inner();
+        assert!(antwort.channels.is_empty());
+        assert_eq!(antwort.page_info.unwrap().total_count, info.total_count);
+    }
+}
@@ -3,19 +3,30 @@
 //!
 //! Implementiert drei Zugangsarten zum Server:
 //! - **REST** (/v1/...): Axum HTTP-Server mit JSON-API
-//! - **TCP/TLS**: Line-based ServerQuery-Stil ueber TLS
+//! - **TCP**: Line-based ServerQuery-Stil, optional ueber TLS
 //! - **gRPC**: Tonic-basierte High-Performance-API
 //!
 //! Alle drei Interfaces nutzen denselben [`commands::CommandExecutor`].
+//! REST und gRPC laufen optional ueber TLS mit Client-Zertifikaten ([`tls`]),
+//! der TCP-Zugang optional ueber TLS.
+//!
+//! gRPC und TCP lassen sich ueber die Features `grpc` und `tcp` abwaehlen;
+//! REST ist immer enthalten.
 
 pub mod auth;
 pub mod commands;
 pub mod error;
+#[cfg(feature = "grpc")]
 pub mod grpc;
 pub mod rate_limit;
 pub mod rest;
+pub mod sicherung;
+#[cfg(feature = "tcp")]
 pub mod tcp;
+pub mod tls;
+pub mod ts3_import;
+pub mod zeitplaner;
 
-pub use commands::executor::CommandExecutor;
+pub use commands::executor::{CommandExecutor, SignalingNotifier};
 pub use error::{CommanderError, CommanderResult};
 pub use rate_limit::{RateLimitKonfig, RateLimiter};
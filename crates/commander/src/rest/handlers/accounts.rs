@@ -0,0 +1,74 @@
+//! REST-Handler fuer Konto-Endpunkte (Datenexport, Loeschung, Inhaltsbereinigung)
+
+use axum::{
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Json, Response},
+};
+use std::time::Duration;
+use uuid::Uuid;
+
+use crate::commands::types::Command;
+use crate::rest::typen::BereinigungBody;
+use crate::rest::{json_antwort, session_aus_headers, CommanderState};
+
+/// Erstellt den Datenexport des Benutzers `id` und gibt den Einmal-Link zurueck
+pub async fn export_account(
+    State(state): State<CommanderState>,
+    Path(id): Path<Uuid>,
+    headers: HeaderMap,
+) -> Response {
+    let session = match session_aus_headers(&headers, &state) {
+        Ok(s) => s,
+        Err(r) => return r,
+    };
+    match state
+        .ausfuehren(Command::KontoExport { benutzer_id: id }, session)
+        .await
+    {
+        Ok(resp) => json_antwort(StatusCode::CREATED, resp),
+        Err(e) => e.into_response(),
+    }
+}
+
+/// Loescht das Konto des Benutzers `id` endgueltig
+pub async fn delete_account(
+    State(state): State<CommanderState>,
+    Path(id): Path<Uuid>,
+    headers: HeaderMap,
+) -> Response {
+    let session = match session_aus_headers(&headers, &state) {
+        Ok(s) => s,
+        Err(r) => return r,
+    };
+    match state
+        .ausfuehren(Command::KontoLoeschen { benutzer_id: id }, session)
+        .await
+    {
+        Ok(resp) => json_antwort(StatusCode::OK, resp),
+        Err(e) => e.into_response(),
+    }
+}
+
+/// Entfernt Nachrichten und Dateien des Benutzers `id` ohne Ban
+pub async fn cleanup_account(
+    State(state): State<CommanderState>,
+    Path(id): Path<Uuid>,
+    headers: HeaderMap,
+    Json(body): Json<BereinigungBody>,
+) -> Response {
+    let session = match session_aus_headers(&headers, &state) {
+        Ok(s) => s,
+        Err(r) => return r,
+    };
+    let cmd = Command::BenutzerInhalteBereinigen {
+        user_id: id,
+        zeitraum: Duration::from_secs(body.zeitraum_secs.unwrap_or(0)),
+        nur_kanal: body.kanal_id,
+        job_id: body.job_id,
+    };
+    match state.ausfuehren(cmd, session).await {
+        Ok(resp) => json_antwort(StatusCode::OK, resp),
+        Err(e) => e.into_response(),
+    }
+}
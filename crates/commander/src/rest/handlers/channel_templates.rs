@@ -0,0 +1,82 @@
+//! REST-Handler fuer Kanal-Vorlagen (/v1/channel-templates)
+
+use axum::{
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Json, Response},
+};
+use uuid::Uuid;
+
+use crate::commands::types::Command;
+use crate::rest::typen::{InstanziierenBody, VorlageErstellenBody};
+use crate::rest::{json_antwort, session_aus_headers, CommanderState};
+
+pub async fn list_templates(State(state): State<CommanderState>, headers: HeaderMap) -> Response {
+    let session = match session_aus_headers(&headers, &state) {
+        Ok(s) => s,
+        Err(r) => return r,
+    };
+    match state.ausfuehren(Command::VorlageListe, session).await {
+        Ok(resp) => json_antwort(StatusCode::OK, resp),
+        Err(e) => e.into_response(),
+    }
+}
+
+pub async fn create_template(
+    State(state): State<CommanderState>,
+    headers: HeaderMap,
+    Json(body): Json<VorlageErstellenBody>,
+) -> Response {
+    let session = match session_aus_headers(&headers, &state) {
+        Ok(s) => s,
+        Err(r) => return r,
+    };
+    let cmd = Command::VorlageErstellen {
+        name: body.name,
+        beschreibung: body.beschreibung,
+        definition: body.definition,
+    };
+    match state.ausfuehren(cmd, session).await {
+        Ok(resp) => json_antwort(StatusCode::CREATED, resp),
+        Err(e) => e.into_response(),
+    }
+}
+
+pub async fn delete_template(
+    State(state): State<CommanderState>,
+    Path(id): Path<Uuid>,
+    headers: HeaderMap,
+) -> Response {
+    let session = match session_aus_headers(&headers, &state) {
+        Ok(s) => s,
+        Err(r) => return r,
+    };
+    match state
+        .ausfuehren(Command::VorlageLoeschen { id }, session)
+        .await
+    {
+        Ok(_) => StatusCode::NO_CONTENT.into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+pub async fn instantiate_template(
+    State(state): State<CommanderState>,
+    Path(id): Path<Uuid>,
+    headers: HeaderMap,
+    Json(body): Json<InstanziierenBody>,
+) -> Response {
+    let session = match session_aus_headers(&headers, &state) {
+        Ok(s) => s,
+        Err(r) => return r,
+    };
+    let cmd = Command::KanalbaumAusVorlage {
+        vorlage_id: id,
+        parent_id: body.parent_id,
+        name_prefix: body.name_prefix,
+    };
+    match state.ausfuehren(cmd, session).await {
+        Ok(resp) => json_antwort(StatusCode::CREATED, resp),
+        Err(e) => e.into_response(),
+    }
+}
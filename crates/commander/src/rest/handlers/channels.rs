@@ -1,43 +1,35 @@
 //! REST-Handler fuer Kanal-Endpunkte
 
 use axum::{
-    extract::{Path, State},
+    extract::{Path, Query, State},
     http::{HeaderMap, StatusCode},
     response::{IntoResponse, Json, Response},
 };
-use serde::Deserialize;
-use serde_json::json;
 use uuid::Uuid;
 
-use crate::commands::types::Command;
-use crate::rest::{session_aus_headers, CommanderState};
+use crate::commands::types::{Command, SeitenAnfrage};
+use crate::rest::typen::{KanalBearbeitenBody, KanalErstellenBody, KanalQuery, NotfallStummBody};
+use crate::rest::{json_antwort, session_aus_headers, CommanderState};
 
-pub async fn list_channels(State(state): State<CommanderState>, headers: HeaderMap) -> Response {
+pub async fn list_channels(
+    State(state): State<CommanderState>,
+    Query(params): Query<KanalQuery>,
+    headers: HeaderMap,
+) -> Response {
     let session = match session_aus_headers(&headers, &state) {
         Ok(s) => s,
         Err(r) => return r,
     };
-    match state.ausfuehren(Command::KanalListe, session).await {
-        Ok(resp) => (StatusCode::OK, Json(serde_json::to_value(resp).unwrap())).into_response(),
-        Err(e) => (
-            StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
-            Json(json!({ "error": e.to_string() })),
-        )
-            .into_response(),
+    let cmd = Command::KanalListe {
+        name: params.name,
+        seite: SeitenAnfrage::neu(params.page, params.page_size),
+    };
+    match state.ausfuehren(cmd, session).await {
+        Ok(resp) => json_antwort(StatusCode::OK, resp),
+        Err(e) => e.into_response(),
     }
 }
 
-#[derive(Debug, Deserialize)]
-pub struct KanalErstellenBody {
-    pub name: String,
-    pub parent_id: Option<Uuid>,
-    pub thema: Option<String>,
-    pub passwort: Option<String>,
-    pub max_clients: Option<i64>,
-    pub sort_order: Option<i64>,
-    pub permanent: Option<bool>,
-}
-
 pub async fn create_channel(
     State(state): State<CommanderState>,
     headers: HeaderMap,
@@ -57,27 +49,11 @@ pub async fn create_channel(
         permanent: body.permanent.unwrap_or(false),
     };
     match state.ausfuehren(cmd, session).await {
-        Ok(resp) => (
-            StatusCode::CREATED,
-            Json(serde_json::to_value(resp).unwrap()),
-        )
-            .into_response(),
-        Err(e) => (
-            StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
-            Json(json!({ "error": e.to_string() })),
-        )
-            .into_response(),
+        Ok(resp) => json_antwort(StatusCode::CREATED, resp),
+        Err(e) => e.into_response(),
     }
 }
 
-#[derive(Debug, Deserialize)]
-pub struct KanalBearbeitenBody {
-    pub name: Option<String>,
-    pub thema: Option<String>,
-    pub max_clients: Option<i64>,
-    pub sort_order: Option<i64>,
-}
-
 pub async fn update_channel(
     State(state): State<CommanderState>,
     Path(id): Path<Uuid>,
@@ -94,14 +70,16 @@ pub async fn update_channel(
         thema: body.thema.map(Some),
         max_clients: body.max_clients,
         sort_order: body.sort_order,
+        langsammodus_sek: body.langsammodus_sek,
+        bearbeitungsfrist_sek: if body.bearbeitungsfrist_standard {
+            Some(None)
+        } else {
+            body.bearbeitungsfrist_sek.map(Some)
+        },
     };
     match state.ausfuehren(cmd, session).await {
-        Ok(resp) => (StatusCode::OK, Json(serde_json::to_value(resp).unwrap())).into_response(),
-        Err(e) => (
-            StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
-            Json(json!({ "error": e.to_string() })),
-        )
-            .into_response(),
+        Ok(resp) => json_antwort(StatusCode::OK, resp),
+        Err(e) => e.into_response(),
     }
 }
 
@@ -119,10 +97,27 @@ pub async fn delete_channel(
         .await
     {
         Ok(_) => StatusCode::NO_CONTENT.into_response(),
-        Err(e) => (
-            StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
-            Json(json!({ "error": e.to_string() })),
-        )
-            .into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+/// Schaltet den Kanal `id` notfall-stumm bzw. hebt die Stummschaltung auf
+pub async fn emergency_mute_channel(
+    State(state): State<CommanderState>,
+    Path(id): Path<Uuid>,
+    headers: HeaderMap,
+    Json(body): Json<NotfallStummBody>,
+) -> Response {
+    let session = match session_aus_headers(&headers, &state) {
+        Ok(s) => s,
+        Err(r) => return r,
+    };
+    let cmd = Command::KanalNotfallStumm {
+        kanal_id: id,
+        aktivieren: body.aktivieren,
+    };
+    match state.ausfuehren(cmd, session).await {
+        Ok(resp) => json_antwort(StatusCode::OK, resp),
+        Err(e) => e.into_response(),
     }
 }
@@ -1,37 +1,37 @@
 //! REST-Handler fuer Client-Endpunkte
 
 use axum::{
-    extract::{Path, State},
+    extract::{Path, Query, State},
     http::{HeaderMap, StatusCode},
     response::{IntoResponse, Json, Response},
 };
-use serde::Deserialize;
-use serde_json::json;
+use std::time::Duration;
 use uuid::Uuid;
 
-use crate::commands::types::Command;
-use crate::rest::{session_aus_headers, CommanderState};
+use crate::commands::types::{Command, Response as CmdResponse, SeitenAnfrage};
+use crate::rest::typen::{BanBody, ClientQuery, KickBody, MoveAllBody, MoveBody, PokeBody};
+use crate::rest::{json_antwort, session_aus_headers, CommanderState};
 
-pub async fn list_clients(State(state): State<CommanderState>, headers: HeaderMap) -> Response {
+pub async fn list_clients(
+    State(state): State<CommanderState>,
+    Query(params): Query<ClientQuery>,
+    headers: HeaderMap,
+) -> Response {
     let session = match session_aus_headers(&headers, &state) {
         Ok(s) => s,
         Err(r) => return r,
     };
-    match state.ausfuehren(Command::ClientListe, session).await {
-        Ok(resp) => (StatusCode::OK, Json(serde_json::to_value(resp).unwrap())).into_response(),
-        Err(e) => (
-            StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
-            Json(json!({ "error": e.to_string() })),
-        )
-            .into_response(),
+    let cmd = Command::ClientListe {
+        kanal_id: params.channel_id,
+        username: params.username,
+        seite: SeitenAnfrage::neu(params.page, params.page_size),
+    };
+    match state.ausfuehren(cmd, session).await {
+        Ok(resp) => json_antwort(StatusCode::OK, resp),
+        Err(e) => e.into_response(),
     }
 }
 
-#[derive(Debug, Deserialize)]
-pub struct KickBody {
-    pub grund: Option<String>,
-}
-
 pub async fn kick_client(
     State(state): State<CommanderState>,
     Path(id): Path<Uuid>,
@@ -53,21 +53,10 @@ pub async fn kick_client(
         .await
     {
         Ok(_) => StatusCode::NO_CONTENT.into_response(),
-        Err(e) => (
-            StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
-            Json(json!({ "error": e.to_string() })),
-        )
-            .into_response(),
+        Err(e) => e.into_response(),
     }
 }
 
-#[derive(Debug, Deserialize)]
-pub struct BanBody {
-    pub grund: Option<String>,
-    pub dauer_secs: Option<u64>,
-    pub ip_bannen: Option<bool>,
-}
-
 pub async fn ban_client(
     State(state): State<CommanderState>,
     Path(id): Path<Uuid>,
@@ -85,25 +74,18 @@ pub async fn ban_client(
                 dauer_secs: body.dauer_secs,
                 grund: body.grund,
                 ip_bannen: body.ip_bannen.unwrap_or(false),
+                inhalte_entfernen: body.inhalte_entfernen_secs.map(Duration::from_secs),
             },
             session,
         )
         .await
     {
+        Ok(resp @ CmdResponse::InhalteBereinigt(_)) => json_antwort(StatusCode::OK, resp),
         Ok(_) => StatusCode::NO_CONTENT.into_response(),
-        Err(e) => (
-            StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
-            Json(json!({ "error": e.to_string() })),
-        )
-            .into_response(),
+        Err(e) => e.into_response(),
     }
 }
 
-#[derive(Debug, Deserialize)]
-pub struct MoveBody {
-    pub kanal_id: Uuid,
-}
-
 pub async fn move_client(
     State(state): State<CommanderState>,
     Path(id): Path<Uuid>,
@@ -125,17 +107,69 @@ pub async fn move_client(
         .await
     {
         Ok(_) => StatusCode::NO_CONTENT.into_response(),
-        Err(e) => (
-            StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
-            Json(json!({ "error": e.to_string() })),
-        )
-            .into_response(),
+        Err(e) => e.into_response(),
     }
 }
 
-#[derive(Debug, Deserialize)]
-pub struct PokeBody {
-    pub nachricht: String,
+/// Gibt die gerade sprechenden Clients des Kanals `id` zurueck
+pub async fn list_speakers(
+    State(state): State<CommanderState>,
+    Path(id): Path<Uuid>,
+    headers: HeaderMap,
+) -> Response {
+    let session = match session_aus_headers(&headers, &state) {
+        Ok(s) => s,
+        Err(r) => return r,
+    };
+    match state
+        .ausfuehren(Command::AktiveSprecher { kanal_id: id }, session)
+        .await
+    {
+        Ok(resp) => json_antwort(StatusCode::OK, resp),
+        Err(e) => e.into_response(),
+    }
+}
+
+/// Gibt die Voice-Diagnose des Clients `id` zurueck
+pub async fn voice_diagnose(
+    State(state): State<CommanderState>,
+    Path(id): Path<Uuid>,
+    headers: HeaderMap,
+) -> Response {
+    let session = match session_aus_headers(&headers, &state) {
+        Ok(s) => s,
+        Err(r) => return r,
+    };
+    match state
+        .ausfuehren(Command::VoiceDiagnose { client_id: id }, session)
+        .await
+    {
+        Ok(resp) => json_antwort(StatusCode::OK, resp),
+        Err(e) => e.into_response(),
+    }
+}
+
+/// Verschiebt alle (oder ausgewaehlte) Clients des Kanals `id`
+pub async fn move_all_clients(
+    State(state): State<CommanderState>,
+    Path(id): Path<Uuid>,
+    headers: HeaderMap,
+    Json(body): Json<MoveAllBody>,
+) -> Response {
+    let session = match session_aus_headers(&headers, &state) {
+        Ok(s) => s,
+        Err(r) => return r,
+    };
+    let cmd = Command::ClientsVerschiebenAlle {
+        von_kanal_id: id,
+        nach_kanal_id: body.nach_kanal_id,
+        nur_user_ids: body.nur_user_ids,
+        teilweise: body.teilweise,
+    };
+    match state.ausfuehren(cmd, session).await {
+        Ok(resp) => json_antwort(StatusCode::OK, resp),
+        Err(e) => e.into_response(),
+    }
 }
 
 pub async fn poke_client(
@@ -159,10 +193,6 @@ pub async fn poke_client(
         .await
     {
         Ok(_) => StatusCode::NO_CONTENT.into_response(),
-        Err(e) => (
-            StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
-            Json(json!({ "error": e.to_string() })),
-        )
-            .into_response(),
+        Err(e) => e.into_response(),
     }
 }
@@ -1,15 +1,15 @@
 //! REST-Handler fuer Datei-Endpunkte
 
 use axum::{
-    extract::{Path, State},
+    extract::{Path, Query, State},
     http::{HeaderMap, StatusCode},
-    response::{IntoResponse, Json, Response},
+    response::{IntoResponse, Response},
 };
-use serde_json::json;
 use uuid::Uuid;
 
 use crate::commands::types::Command;
-use crate::rest::{session_aus_headers, CommanderState};
+use crate::rest::typen::ZugriffsQuery;
+use crate::rest::{json_antwort, session_aus_headers, CommanderState};
 
 pub async fn list_files(
     State(state): State<CommanderState>,
@@ -24,12 +24,8 @@ pub async fn list_files(
         .ausfuehren(Command::DateiListe { kanal_id }, session)
         .await
     {
-        Ok(resp) => (StatusCode::OK, Json(serde_json::to_value(resp).unwrap())).into_response(),
-        Err(e) => (
-            StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
-            Json(json!({ "error": e.to_string() })),
-        )
-            .into_response(),
+        Ok(resp) => json_antwort(StatusCode::OK, resp),
+        Err(e) => e.into_response(),
     }
 }
 
@@ -47,10 +43,34 @@ pub async fn delete_file(
         .await
     {
         Ok(_) => StatusCode::NO_CONTENT.into_response(),
-        Err(e) => (
-            StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
-            Json(json!({ "error": e.to_string() })),
+        Err(e) => e.into_response(),
+    }
+}
+
+pub async fn get_file_access_log(
+    State(state): State<CommanderState>,
+    Query(params): Query<ZugriffsQuery>,
+    headers: HeaderMap,
+) -> Response {
+    let session = match session_aus_headers(&headers, &state) {
+        Ok(s) => s,
+        Err(r) => return r,
+    };
+    match state
+        .ausfuehren(
+            Command::DateiZugriffe {
+                datei_id: params.datei_id,
+                benutzer_id: params.benutzer_id,
+                von: params.von,
+                bis: params.bis,
+                limit: params.limit.unwrap_or(50).min(1000),
+                offset: params.offset.unwrap_or(0),
+            },
+            session,
         )
-            .into_response(),
+        .await
+    {
+        Ok(resp) => json_antwort(StatusCode::OK, resp),
+        Err(e) => e.into_response(),
     }
 }
@@ -3,20 +3,12 @@
 use axum::{
     extract::{Query, State},
     http::{HeaderMap, StatusCode},
-    response::{IntoResponse, Json, Response},
+    response::{IntoResponse, Response},
 };
-use serde::Deserialize;
-use serde_json::json;
 
 use crate::commands::types::Command;
-use crate::rest::{session_aus_headers, CommanderState};
-
-#[derive(Debug, Deserialize)]
-pub struct LogQuery {
-    pub limit: Option<u32>,
-    pub offset: Option<u32>,
-    pub aktion: Option<String>,
-}
+use crate::rest::typen::LogQuery;
+use crate::rest::{json_antwort, session_aus_headers, CommanderState};
 
 pub async fn get_logs(
     State(state): State<CommanderState>,
@@ -38,11 +30,7 @@ pub async fn get_logs(
         )
         .await
     {
-        Ok(resp) => (StatusCode::OK, Json(serde_json::to_value(resp).unwrap())).into_response(),
-        Err(e) => (
-            StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
-            Json(json!({ "error": e.to_string() })),
-        )
-            .into_response(),
+        Ok(resp) => json_antwort(StatusCode::OK, resp),
+        Err(e) => e.into_response(),
     }
 }
@@ -0,0 +1,30 @@
+//! REST-Handler fuer Chat-Nachrichten
+
+use axum::{
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+};
+use uuid::Uuid;
+
+use crate::commands::types::Command;
+use crate::rest::{json_antwort, session_aus_headers, CommanderState};
+
+/// Gibt die Nachricht `id` samt Bearbeitungszaehler zurueck
+pub async fn get_message(
+    State(state): State<CommanderState>,
+    Path(id): Path<Uuid>,
+    headers: HeaderMap,
+) -> Response {
+    let session = match session_aus_headers(&headers, &state) {
+        Ok(s) => s,
+        Err(r) => return r,
+    };
+    match state
+        .ausfuehren(Command::NachrichtInfo { nachricht_id: id }, session)
+        .await
+    {
+        Ok(resp) => json_antwort(StatusCode::OK, resp),
+        Err(e) => e.into_response(),
+    }
+}
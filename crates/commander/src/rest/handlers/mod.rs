@@ -1,8 +1,14 @@
 //! REST-Handler Module
 
+pub mod accounts;
+pub mod channel_templates;
 pub mod channels;
 pub mod clients;
 pub mod files;
 pub mod logs;
+pub mod messages;
 pub mod permissions;
+pub mod schedules;
 pub mod server;
+pub mod soundboard;
+pub mod users;
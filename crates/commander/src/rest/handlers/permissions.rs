@@ -1,15 +1,15 @@
 //! REST-Handler fuer Berechtigungs-Endpunkte
 
 use axum::{
-    extract::{Path, State},
+    extract::{Path, Query, State},
     http::{HeaderMap, StatusCode},
     response::{IntoResponse, Json, Response},
 };
-use serde::Deserialize;
-use serde_json::json;
+use uuid::Uuid;
 
-use crate::commands::types::{BerechtigungsWertInput, Command};
-use crate::rest::{session_aus_headers, CommanderState};
+use crate::commands::types::Command;
+use crate::rest::typen::{EffektivQuery, RemovePermissionBody, SetPermissionBody};
+use crate::rest::{json_antwort, session_aus_headers, CommanderState};
 
 pub async fn get_permissions(
     State(state): State<CommanderState>,
@@ -30,21 +30,34 @@ pub async fn get_permissions(
         )
         .await
     {
-        Ok(resp) => (StatusCode::OK, Json(serde_json::to_value(resp).unwrap())).into_response(),
-        Err(e) => (
-            StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
-            Json(json!({ "error": e.to_string() })),
-        )
-            .into_response(),
+        Ok(resp) => json_antwort(StatusCode::OK, resp),
+        Err(e) => e.into_response(),
     }
 }
 
-#[derive(Debug, Deserialize)]
-pub struct SetPermissionBody {
-    pub ziel: String,
-    pub permission: String,
-    pub wert: BerechtigungsWertInput,
-    pub scope: Option<String>,
+pub async fn get_effective_permissions(
+    State(state): State<CommanderState>,
+    Path(user_id): Path<Uuid>,
+    Query(params): Query<EffektivQuery>,
+    headers: HeaderMap,
+) -> Response {
+    let session = match session_aus_headers(&headers, &state) {
+        Ok(s) => s,
+        Err(r) => return r,
+    };
+    match state
+        .ausfuehren(
+            Command::BerechtigungEffektiv {
+                user_id,
+                kanal_id: params.channel,
+            },
+            session,
+        )
+        .await
+    {
+        Ok(resp) => json_antwort(StatusCode::OK, resp),
+        Err(e) => e.into_response(),
+    }
 }
 
 pub async fn set_permission(
@@ -69,20 +82,10 @@ pub async fn set_permission(
         .await
     {
         Ok(_) => StatusCode::NO_CONTENT.into_response(),
-        Err(e) => (
-            StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
-            Json(json!({ "error": e.to_string() })),
-        )
-            .into_response(),
+        Err(e) => e.into_response(),
     }
 }
 
-#[derive(Debug, Deserialize)]
-pub struct RemovePermissionBody {
-    pub ziel: String,
-    pub scope: Option<String>,
-}
-
 pub async fn remove_permission(
     State(state): State<CommanderState>,
     Path(permission): Path<String>,
@@ -105,10 +108,6 @@ pub async fn remove_permission(
         .await
     {
         Ok(_) => StatusCode::NO_CONTENT.into_response(),
-        Err(e) => (
-            StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
-            Json(json!({ "error": e.to_string() })),
-        )
-            .into_response(),
+        Err(e) => e.into_response(),
     }
 }
@@ -0,0 +1,63 @@
+//! REST-Handler fuer geplante Aktionen (/v1/schedules)
+
+use axum::{
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Json, Response},
+};
+use uuid::Uuid;
+
+use crate::commands::types::Command;
+use crate::rest::typen::ZeitplanErstellenBody;
+use crate::rest::{json_antwort, session_aus_headers, CommanderState};
+
+pub async fn list_schedules(State(state): State<CommanderState>, headers: HeaderMap) -> Response {
+    let session = match session_aus_headers(&headers, &state) {
+        Ok(s) => s,
+        Err(r) => return r,
+    };
+    match state.ausfuehren(Command::ZeitplanListe, session).await {
+        Ok(resp) => json_antwort(StatusCode::OK, resp),
+        Err(e) => e.into_response(),
+    }
+}
+
+pub async fn create_schedule(
+    State(state): State<CommanderState>,
+    headers: HeaderMap,
+    Json(body): Json<ZeitplanErstellenBody>,
+) -> Response {
+    let session = match session_aus_headers(&headers, &state) {
+        Ok(s) => s,
+        Err(r) => return r,
+    };
+    let cmd = Command::ZeitplanErstellen {
+        name: body.name,
+        aktion: body.aktion,
+        einmalig: body.einmalig,
+        cron: body.cron,
+        zeitzone: body.zeitzone,
+    };
+    match state.ausfuehren(cmd, session).await {
+        Ok(resp) => json_antwort(StatusCode::CREATED, resp),
+        Err(e) => e.into_response(),
+    }
+}
+
+pub async fn cancel_schedule(
+    State(state): State<CommanderState>,
+    Path(id): Path<Uuid>,
+    headers: HeaderMap,
+) -> Response {
+    let session = match session_aus_headers(&headers, &state) {
+        Ok(s) => s,
+        Err(r) => return r,
+    };
+    match state
+        .ausfuehren(Command::ZeitplanAbbrechen { id }, session)
+        .await
+    {
+        Ok(_) => StatusCode::NO_CONTENT.into_response(),
+        Err(e) => e.into_response(),
+    }
+}
@@ -1,15 +1,16 @@
 //! REST-Handler fuer Server-Endpunkte
 
 use axum::{
-    extract::State,
+    extract::{Query, State},
     http::{HeaderMap, StatusCode},
     response::{IntoResponse, Json, Response},
 };
-use serde::Deserialize;
-use serde_json::json;
 
 use crate::commands::types::Command;
-use crate::rest::{session_aus_headers, CommanderState};
+use crate::rest::typen::{
+    BackupBody, HistorieQuery, MotdBody, ServerBearbeitenBody, ServerStoppenBody,
+};
+use crate::rest::{json_antwort, session_aus_headers, CommanderState};
 
 pub async fn get_server(State(state): State<CommanderState>, headers: HeaderMap) -> Response {
     let session = match session_aus_headers(&headers, &state) {
@@ -17,21 +18,27 @@ pub async fn get_server(State(state): State<CommanderState>, headers: HeaderMap)
         Err(r) => return r,
     };
     match state.ausfuehren(Command::ServerInfo, session).await {
-        Ok(resp) => (StatusCode::OK, Json(serde_json::to_value(resp).unwrap())).into_response(),
-        Err(e) => (
-            StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
-            Json(json!({ "error": e.to_string() })),
-        )
-            .into_response(),
+        Ok(resp) => json_antwort(StatusCode::OK, resp),
+        Err(e) => e.into_response(),
     }
 }
 
-#[derive(Debug, Deserialize)]
-pub struct ServerBearbeitenBody {
-    pub name: Option<String>,
-    pub willkommensnachricht: Option<String>,
-    pub max_clients: Option<u32>,
-    pub host_nachricht: Option<String>,
+pub async fn get_server_history(
+    State(state): State<CommanderState>,
+    Query(params): Query<HistorieQuery>,
+    headers: HeaderMap,
+) -> Response {
+    let session = match session_aus_headers(&headers, &state) {
+        Ok(s) => s,
+        Err(r) => return r,
+    };
+    let cmd = Command::ServerHistorie {
+        limit: params.limit.unwrap_or(20).min(200),
+    };
+    match state.ausfuehren(cmd, session).await {
+        Ok(resp) => json_antwort(StatusCode::OK, resp),
+        Err(e) => e.into_response(),
+    }
 }
 
 pub async fn put_server(
@@ -48,22 +55,16 @@ pub async fn put_server(
         willkommensnachricht: body.willkommensnachricht,
         max_clients: body.max_clients,
         host_nachricht: body.host_nachricht,
+        afk_timeout_sek: body.afk_timeout_sek,
+        afk_kanal_id: body.afk_kanal_id,
+        chat_bearbeitungsfrist_sek: body.chat_bearbeitungsfrist_sek,
     };
     match state.ausfuehren(cmd, session).await {
         Ok(_) => StatusCode::NO_CONTENT.into_response(),
-        Err(e) => (
-            StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
-            Json(json!({ "error": e.to_string() })),
-        )
-            .into_response(),
+        Err(e) => e.into_response(),
     }
 }
 
-#[derive(Debug, Deserialize)]
-pub struct ServerStoppenBody {
-    pub grund: Option<String>,
-}
-
 pub async fn post_server_stop(
     State(state): State<CommanderState>,
     headers: HeaderMap,
@@ -78,10 +79,58 @@ pub async fn post_server_stop(
         .await
     {
         Ok(_) => StatusCode::ACCEPTED.into_response(),
-        Err(e) => (
-            StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
-            Json(json!({ "error": e.to_string() })),
-        )
-            .into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+pub async fn post_server_backup(
+    State(state): State<CommanderState>,
+    headers: HeaderMap,
+    Json(body): Json<BackupBody>,
+) -> Response {
+    let session = match session_aus_headers(&headers, &state) {
+        Ok(s) => s,
+        Err(r) => return r,
+    };
+    let cmd = Command::BackupErstellen {
+        ziel_pfad: body.ziel_pfad,
+        include_files: body.include_files,
+    };
+    match state.ausfuehren(cmd, session).await {
+        Ok(resp) => json_antwort(StatusCode::ACCEPTED, resp),
+        Err(e) => e.into_response(),
+    }
+}
+
+pub async fn put_server_motd(
+    State(state): State<CommanderState>,
+    headers: HeaderMap,
+    Json(body): Json<MotdBody>,
+) -> Response {
+    let session = match session_aus_headers(&headers, &state) {
+        Ok(s) => s,
+        Err(r) => return r,
+    };
+    let cmd = Command::MotdSetzen {
+        markdown: body.markdown,
+    };
+    match state.ausfuehren(cmd, session).await {
+        Ok(resp) => json_antwort(StatusCode::OK, resp),
+        Err(e) => e.into_response(),
+    }
+}
+
+/// Wertet alle Alarmregeln sofort aus und liefert ihren Stand
+pub async fn post_server_alerts_test(
+    State(state): State<CommanderState>,
+    headers: HeaderMap,
+) -> Response {
+    let session = match session_aus_headers(&headers, &state) {
+        Ok(s) => s,
+        Err(r) => return r,
+    };
+    match state.ausfuehren(Command::AlertRegelnTesten, session).await {
+        Ok(resp) => json_antwort(StatusCode::OK, resp),
+        Err(e) => e.into_response(),
     }
 }
@@ -0,0 +1,73 @@
+//! REST-Handler fuer das Soundboard (/v1/soundboard)
+
+use axum::{
+    extract::{Path, Query, State},
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Json, Response},
+};
+use uuid::Uuid;
+
+use crate::commands::types::Command;
+use crate::rest::typen::{SoundQuery, SoundRegistrierenBody};
+use crate::rest::{json_antwort, session_aus_headers, CommanderState};
+
+pub async fn list_sounds(
+    State(state): State<CommanderState>,
+    Query(params): Query<SoundQuery>,
+    headers: HeaderMap,
+) -> Response {
+    let session = match session_aus_headers(&headers, &state) {
+        Ok(s) => s,
+        Err(r) => return r,
+    };
+    match state
+        .ausfuehren(
+            Command::SoundboardListe {
+                kanal_id: params.channel,
+            },
+            session,
+        )
+        .await
+    {
+        Ok(resp) => json_antwort(StatusCode::OK, resp),
+        Err(e) => e.into_response(),
+    }
+}
+
+pub async fn register_sound(
+    State(state): State<CommanderState>,
+    headers: HeaderMap,
+    Json(body): Json<SoundRegistrierenBody>,
+) -> Response {
+    let session = match session_aus_headers(&headers, &state) {
+        Ok(s) => s,
+        Err(r) => return r,
+    };
+    let cmd = Command::SoundboardRegistrieren {
+        name: body.name,
+        datei_id: body.datei_id,
+        kanal_id: body.kanal_id,
+    };
+    match state.ausfuehren(cmd, session).await {
+        Ok(resp) => json_antwort(StatusCode::CREATED, resp),
+        Err(e) => e.into_response(),
+    }
+}
+
+pub async fn delete_sound(
+    State(state): State<CommanderState>,
+    Path(id): Path<Uuid>,
+    headers: HeaderMap,
+) -> Response {
+    let session = match session_aus_headers(&headers, &state) {
+        Ok(s) => s,
+        Err(r) => return r,
+    };
+    match state
+        .ausfuehren(Command::SoundboardEntfernen { id }, session)
+        .await
+    {
+        Ok(_) => StatusCode::NO_CONTENT.into_response(),
+        Err(e) => e.into_response(),
+    }
+}
@@ -0,0 +1,96 @@
+//! REST-Handler fuer die Benutzerverwaltung (Liste, Passwort, Sperren, Entfernen)
+
+use axum::{
+    extract::{Path, Query, State},
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Json, Response},
+};
+use uuid::Uuid;
+
+use crate::commands::types::Command;
+use crate::rest::typen::{BenutzerQuery, DeaktivierenBody, PasswortSetzenBody};
+use crate::rest::{json_antwort, session_aus_headers, CommanderState};
+
+/// Listet alle Benutzerkonten, optional nach Namen gefiltert
+pub async fn list_users(
+    State(state): State<CommanderState>,
+    Query(params): Query<BenutzerQuery>,
+    headers: HeaderMap,
+) -> Response {
+    let session = match session_aus_headers(&headers, &state) {
+        Ok(s) => s,
+        Err(r) => return r,
+    };
+    let cmd = Command::BenutzerListe {
+        filter: params.filter,
+        limit: params.limit.unwrap_or(50).min(1000),
+        offset: params.offset.unwrap_or(0),
+    };
+    match state.ausfuehren(cmd, session).await {
+        Ok(resp) => json_antwort(StatusCode::OK, resp),
+        Err(e) => e.into_response(),
+    }
+}
+
+/// Setzt das Passwort des Benutzers `id` ohne Kenntnis des alten Passworts
+pub async fn set_user_password(
+    State(state): State<CommanderState>,
+    Path(id): Path<Uuid>,
+    headers: HeaderMap,
+    Json(body): Json<PasswortSetzenBody>,
+) -> Response {
+    let session = match session_aus_headers(&headers, &state) {
+        Ok(s) => s,
+        Err(r) => return r,
+    };
+    let cmd = Command::BenutzerPasswortSetzen {
+        user_id: id,
+        neues_passwort: body.neues_passwort,
+    };
+    match state.ausfuehren(cmd, session).await {
+        Ok(resp) => json_antwort(StatusCode::OK, resp),
+        Err(e) => e.into_response(),
+    }
+}
+
+/// Sperrt den Benutzer `id` oder schaltet ihn wieder frei
+pub async fn disable_user(
+    State(state): State<CommanderState>,
+    Path(id): Path<Uuid>,
+    headers: HeaderMap,
+    Json(body): Json<DeaktivierenBody>,
+) -> Response {
+    let session = match session_aus_headers(&headers, &state) {
+        Ok(s) => s,
+        Err(r) => return r,
+    };
+    let cmd = Command::BenutzerDeaktivieren {
+        user_id: id,
+        deaktiviert: body.deaktiviert,
+    };
+    match state.ausfuehren(cmd, session).await {
+        Ok(resp) => json_antwort(StatusCode::OK, resp),
+        Err(e) => e.into_response(),
+    }
+}
+
+/// Entfernt den Benutzer `id` (Konto deaktiviert, Zugaenge widerrufen)
+///
+/// Die endgueltige Loeschung aller Daten uebernimmt `DELETE /v1/users/:id`.
+pub async fn remove_user(
+    State(state): State<CommanderState>,
+    Path(id): Path<Uuid>,
+    headers: HeaderMap,
+) -> Response {
+    let session = match session_aus_headers(&headers, &state) {
+        Ok(s) => s,
+        Err(r) => return r,
+    };
+    match state
+        .ausfuehren(Command::BenutzerLoeschen { user_id: id }, session)
+        .await
+    {
+        Ok(resp) => json_antwort(StatusCode::OK, resp),
+        Err(e) => e.into_response(),
+    }
+}
@@ -4,6 +4,7 @@ pub mod handlers;
 pub mod middleware;
 pub mod routes;
 pub mod server;
+pub mod typen;
 
 use std::{future::Future, pin::Pin, sync::Arc};
 
@@ -12,10 +13,15 @@ use axum::{
     response::{IntoResponse, Json, Response},
 };
 use serde_json::json;
+use speakeasy_core::SpeakeasyError;
+use speakeasy_db::zeitlimit::{
+    self, ZeitlimitMetriken, ZeitlimitStatistik, Zeitlimits, Zugriffsart,
+};
 
 use crate::auth::CommanderSession;
 use crate::commands::types::{Command, Response as CmdResponse};
 use crate::error::{CommanderError, CommanderResult};
+use crate::tls::ClientIdentitaet;
 
 /// Typ-Alias fuer eine geboxte Send-Future
 pub type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
@@ -31,11 +37,30 @@ pub type ExecutorFn = Arc<
 pub type TokenValidatorFn =
     Arc<dyn Fn(&str) -> Result<CommanderSession, CommanderError> + Send + Sync>;
 
+/// Funktor-Typ: weist einen Benutzer per Client-Zertifikat aus (synchron)
+pub type ZertifikatsValidatorFn =
+    Arc<dyn Fn(&ClientIdentitaet) -> Result<CommanderSession, CommanderError> + Send + Sync>;
+
+tokio::task_local! {
+    /// Client-Zertifikat der TLS-Verbindung, ueber die die Anfrage kam
+    ///
+    /// Wird vom REST-Server pro Anfrage gesetzt, damit
+    /// [`session_aus_headers`] ohne zusaetzlichen Extractor darauf zugreift.
+    pub(crate) static CLIENT_IDENTITAET: Option<ClientIdentitaet>;
+}
+
 /// Axum-State fuer den Commander-REST-Server
+///
+/// Wird auch von TCP und gRPC genutzt – `ausfuehren` ist damit der gemeinsame
+/// Einstiegspunkt, an dem Zeitlimits und Abbruchverhalten gelten.
 #[derive(Clone)]
 pub struct CommanderState {
     pub executor: ExecutorFn,
     pub token_validator: TokenValidatorFn,
+    /// Gesetzt wenn Client-Zertifikate den Bearer-Token ersetzen duerfen
+    pub zertifikats_validator: Option<ZertifikatsValidatorFn>,
+    pub zeitlimits: Zeitlimits,
+    pub zeitlimit_metriken: Arc<ZeitlimitMetriken>,
 }
 
 impl CommanderState {
@@ -43,46 +68,501 @@ impl CommanderState {
         Self {
             executor,
             token_validator,
+            zertifikats_validator: None,
+            zeitlimits: Zeitlimits::default(),
+            zeitlimit_metriken: Arc::new(ZeitlimitMetriken::neu()),
         }
     }
 
-    /// Fuehrt einen Befehl aus
+    /// Setzt abweichende Zeitlimits fuer lesende/schreibende Befehle
+    pub fn mit_zeitlimits(mut self, zeitlimits: Zeitlimits) -> Self {
+        self.zeitlimits = zeitlimits;
+        self
+    }
+
+    /// Laesst verifizierte Client-Zertifikate anstelle eines Tokens zu
+    ///
+    /// Ohne diesen Validator dient mTLS nur als zusaetzliche Huerde: der
+    /// Bearer-Token bleibt Pflicht.
+    pub fn mit_zertifikats_validator(mut self, validator: ZertifikatsValidatorFn) -> Self {
+        self.zertifikats_validator = Some(validator);
+        self
+    }
+
+    /// Session aus einem Client-Zertifikat (`None` = nicht anwendbar)
+    ///
+    /// Nur fuer Anfragen ohne Bearer-Token: ein vorhandener Token hat immer
+    /// Vorrang.
+    pub fn zertifikats_session(
+        &self,
+        identitaet: Option<&ClientIdentitaet>,
+    ) -> Option<CommanderResult<CommanderSession>> {
+        let validator = self.zertifikats_validator.as_ref()?;
+        Some(validator(identitaet?))
+    }
+
+    /// Zaehler fuer Zeitueberschreitungen und abgebrochene Befehle
+    pub fn zeitlimit_statistik(&self) -> ZeitlimitStatistik {
+        self.zeitlimit_metriken.statistik()
+    }
+
+    /// Fuehrt einen Befehl mit Zeitlimit aus
+    ///
+    /// Lesende Befehle laufen in der Future des Aufrufers: trennt der Client
+    /// die Verbindung (axum/tonic verwerfen die Future) oder laeuft das
+    /// Zeitlimit ab, endet die Arbeit sofort. Schreibende Befehle laufen als
+    /// eigener Task und werden immer zu Ende gefuehrt – nur ihr Ergebnis wird
+    /// bei Abbruch oder Zeitueberschreitung verworfen.
     pub fn ausfuehren(
         &self,
         cmd: Command,
         session: CommanderSession,
     ) -> BoxFuture<'static, CommanderResult<CmdResponse>> {
-        (self.executor)(cmd, session)
+        let art = cmd.zugriffsart();
+        let zeitlimits = self.zeitlimits;
+        let metriken = Arc::clone(&self.zeitlimit_metriken);
+        let arbeit = (self.executor)(cmd, session);
+
+        Box::pin(async move {
+            match art {
+                Zugriffsart::Lesen => {
+                    zeitlimit::begrenzen(art, &zeitlimits, &metriken, arbeit).await?
+                }
+                Zugriffsart::Schreiben => {
+                    let task = tokio::spawn(arbeit);
+                    zeitlimit::begrenzen(art, &zeitlimits, &metriken, task)
+                        .await?
+                        .map_err(|e| {
+                            CommanderError::Intern(anyhow::anyhow!("Befehl abgebrochen: {e}"))
+                        })?
+                }
+            }
+        })
     }
 }
 
 /// Extrahiert Session aus Axum-Request-Headern
+///
+/// Ohne Bearer-Token gilt das Client-Zertifikat der Verbindung, sofern
+/// Zertifikate den Token ersetzen duerfen.
 pub fn session_aus_headers(
     headers: &axum::http::HeaderMap,
     state: &CommanderState,
 ) -> Result<CommanderSession, Response> {
-    let token = headers
-        .get("authorization")
-        .and_then(|v| v.to_str().ok())
-        .and_then(|s| s.strip_prefix("Bearer "))
-        .ok_or_else(|| {
-            (
-                StatusCode::UNAUTHORIZED,
-                Json(json!({ "error": { "code": 401, "message": "Authorization-Header fehlt" } })),
-            )
-                .into_response()
-        })?;
-
-    (state.token_validator)(token).map_err(|_| {
+    let nicht_angemeldet = |nachricht: &str| {
         (
             StatusCode::UNAUTHORIZED,
-            Json(json!({ "error": { "code": 401, "message": "Ungueltiger oder abgelaufener Token" } })),
+            Json(json!({ "error": { "code": 401, "message": nachricht } })),
         )
             .into_response()
-    })
+    };
+    let Some(token) = headers
+        .get("authorization")
+        .and_then(|v| v.to_str().ok())
+        .and_then(|s| s.strip_prefix("Bearer "))
+    else {
+        let identitaet = CLIENT_IDENTITAET.try_with(Clone::clone).ok().flatten();
+        return match state.zertifikats_session(identitaet.as_ref()) {
+            Some(session) => session.map_err(|e| nicht_angemeldet(&e.to_string())),
+            None => Err(nicht_angemeldet("Authorization-Header fehlt")),
+        };
+    };
+
+    (state.token_validator)(token)
+        .map_err(|_| nicht_angemeldet("Ungueltiger oder abgelaufener Token"))
+}
+
+/// JSON-Antwort mit der Nutzlast eines Befehls ([`CmdResponse::nutzlast`])
+pub fn json_antwort(status: StatusCode, resp: CmdResponse) -> Response {
+    match resp.nutzlast() {
+        Ok(nutzlast) => (status, Json(nutzlast)).into_response(),
+        Err(e) => CommanderError::Intern(anyhow::anyhow!("Antwort nicht serialisierbar: {e}"))
+            .into_response(),
+    }
+}
+
+/// Fehler-Envelope fuer REST-Antworten
+///
+/// Statuscode und Code stammen aus der Fehler-Taxonomie:
+/// `{"error": "...", "code": "channel.full", "nummer": 3002}`.
+impl IntoResponse for CommanderError {
+    fn into_response(self) -> Response {
+        let fehler = SpeakeasyError::from(self);
+        let status = StatusCode::from_u16(fehler.code().http_status())
+            .unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+        let mut rumpf = fehler.details();
+        rumpf["error"] = json!(fehler.to_string());
+        (status, Json(rumpf)).into_response()
+    }
 }
 
 // Fuer AppStateT-Kompatibilitaet (Typ-Alias fuer Abwaertskompatibilitaet)
 pub use CommanderState as AppState;
 
 pub use server::RestServer;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::AuthArt;
+    use crate::commands::CommandExecutor;
+    use speakeasy_auth::{ApiTokenStore, AuthService, BanService, PermissionService, SessionStore};
+    use speakeasy_db::{
+        einstellungen::ServerEinstellungen,
+        models::{
+            KanalFilter, KanalRecord, KanalUpdate, KanalbaumGrenzen, NeuerBenutzer, NeuerKanal,
+        },
+        ChannelRepository, DbResult, SqliteDb, UserRepository,
+    };
+    use std::time::Duration;
+    use uuid::Uuid;
+
+    /// Kanal-Repository, das jeden Aufruf kuenstlich verzoegert
+    struct LangsamesKanalRepo {
+        db: Arc<SqliteDb>,
+        verzoegerung: Duration,
+    }
+
+    impl ChannelRepository for LangsamesKanalRepo {
+        async fn create(&self, data: NeuerKanal<'_>) -> DbResult<KanalRecord> {
+            tokio::time::sleep(self.verzoegerung).await;
+            ChannelRepository::create(self.db.as_ref(), data).await
+        }
+        async fn get_by_id(&self, id: Uuid) -> DbResult<Option<KanalRecord>> {
+            tokio::time::sleep(self.verzoegerung).await;
+            ChannelRepository::get_by_id(self.db.as_ref(), id).await
+        }
+        async fn list(&self) -> DbResult<Vec<KanalRecord>> {
+            tokio::time::sleep(self.verzoegerung).await;
+            ChannelRepository::list(self.db.as_ref()).await
+        }
+        async fn list_filtered(&self, filter: KanalFilter) -> DbResult<Vec<KanalRecord>> {
+            tokio::time::sleep(self.verzoegerung).await;
+            ChannelRepository::list_filtered(self.db.as_ref(), filter).await
+        }
+        async fn count_filtered(&self, filter: KanalFilter) -> DbResult<i64> {
+            tokio::time::sleep(self.verzoegerung).await;
+            ChannelRepository::count_filtered(self.db.as_ref(), filter).await
+        }
+        async fn update(&self, id: Uuid, data: KanalUpdate) -> DbResult<KanalRecord> {
+            tokio::time::sleep(self.verzoegerung).await;
+            ChannelRepository::update(self.db.as_ref(), id, data).await
+        }
+        async fn delete(&self, id: Uuid) -> DbResult<bool> {
+            tokio::time::sleep(self.verzoegerung).await;
+            ChannelRepository::delete(self.db.as_ref(), id).await
+        }
+        async fn get_children(&self, parent_id: Uuid) -> DbResult<Vec<KanalRecord>> {
+            tokio::time::sleep(self.verzoegerung).await;
+            ChannelRepository::get_children(self.db.as_ref(), parent_id).await
+        }
+        async fn get_default(&self) -> DbResult<Option<KanalRecord>> {
+            tokio::time::sleep(self.verzoegerung).await;
+            ChannelRepository::get_default(self.db.as_ref()).await
+        }
+    }
+
+    const LIMITS: Zeitlimits = Zeitlimits {
+        lesen: Duration::from_millis(100),
+        schreiben: Duration::from_millis(200),
+    };
+
+    async fn state_mit_verzoegerung(verzoegerung: Duration) -> (CommanderState, Arc<SqliteDb>) {
+        let db = Arc::new(SqliteDb::in_memory().await.unwrap());
+        let executor = CommandExecutor::neu(
+            Arc::clone(&db),
+            Arc::new(LangsamesKanalRepo {
+                db: Arc::clone(&db),
+                verzoegerung,
+            }),
+            Arc::clone(&db),
+            Arc::clone(&db),
+            Arc::clone(&db),
+            Arc::clone(&db),
+            Arc::clone(&db),
+            Arc::clone(&db),
+            Arc::clone(&db),
+            Arc::new(AuthService::neu(
+                Arc::clone(&db),
+                SessionStore::neu(),
+                ApiTokenStore::neu(),
+            )),
+            PermissionService::neu(Arc::clone(&db)),
+            BanService::neu(Arc::clone(&db)),
+            ServerEinstellungen {
+                name: "Test".into(),
+                willkommensnachricht: None,
+                max_clients: 32,
+                host_nachricht: None,
+                chat_bearbeitungsfrist_sek: 0,
+            },
+            "0.0.0".into(),
+            KanalbaumGrenzen::default(),
+        );
+        executor.features_setzen(vec!["grpc".into()]);
+        let executor_fn: ExecutorFn = Arc::new(move |cmd, session| {
+            let exec = Arc::clone(&executor);
+            Box::pin(async move { exec.ausfuehren(cmd, &session).await })
+        });
+        let validator: TokenValidatorFn =
+            Arc::new(|_| Err(CommanderError::Authentifizierung("unbenutzt".into())));
+        let state = CommanderState::neu(executor_fn, validator).mit_zeitlimits(LIMITS);
+        (state, db)
+    }
+
+    async fn session(db: &SqliteDb) -> CommanderSession {
+        let benutzer = UserRepository::create(
+            db,
+            NeuerBenutzer {
+                username: "admin",
+                password_hash: "hash",
+            },
+        )
+        .await
+        .unwrap();
+        CommanderSession {
+            benutzer,
+            scopes: vec![],
+            auth_art: AuthArt::Session,
+        }
+    }
+
+    fn kanal_liste() -> Command {
+        Command::KanalListe {
+            name: None,
+            seite: Default::default(),
+        }
+    }
+
+    fn kanal_erstellen(name: &str) -> Command {
+        Command::KanalErstellen {
+            name: name.into(),
+            parent_id: None,
+            thema: None,
+            passwort: None,
+            max_clients: 0,
+            sort_order: 0,
+            permanent: false,
+        }
+    }
+
+    #[tokio::test]
+    async fn langsames_lesen_liefert_504() {
+        let (state, db) = state_mit_verzoegerung(Duration::from_millis(500)).await;
+        let session = session(&db).await;
+
+        let fehler = state.ausfuehren(kanal_liste(), session).await.unwrap_err();
+        assert!(matches!(fehler, CommanderError::Zeitueberschreitung(_)));
+        assert_eq!(fehler.fehler_code(), 5004);
+        assert_eq!(fehler.into_response().status(), StatusCode::GATEWAY_TIMEOUT);
+        assert_eq!(state.zeitlimit_statistik().zeitueberschreitungen_lesen, 1);
+    }
+
+    #[tokio::test]
+    async fn schnelle_befehle_innerhalb_des_limits() {
+        let (state, db) = state_mit_verzoegerung(Duration::ZERO).await;
+        let session = session(&db).await;
+
+        state
+            .ausfuehren(kanal_erstellen("Lobby"), session.clone())
+            .await
+            .unwrap();
+        assert!(state.ausfuehren(kanal_liste(), session).await.is_ok());
+        assert_eq!(state.zeitlimit_statistik(), ZeitlimitStatistik::default());
+    }
+
+    #[tokio::test]
+    async fn zeitueberschrittenes_schreiben_wird_zu_ende_gefuehrt() {
+        let (state, db) = state_mit_verzoegerung(Duration::from_millis(400)).await;
+        let session = session(&db).await;
+        let vorher = ChannelRepository::list(db.as_ref()).await.unwrap().len();
+
+        let fehler = state
+            .ausfuehren(kanal_erstellen("Spaet"), session)
+            .await
+            .unwrap_err();
+        assert!(matches!(fehler, CommanderError::Zeitueberschreitung(_)));
+        assert_eq!(
+            state.zeitlimit_statistik().zeitueberschreitungen_schreiben,
+            1
+        );
+
+        // Der Task laeuft weiter; das Ergebnis wird nur verworfen
+        tokio::time::sleep(Duration::from_millis(800)).await;
+        let kanaele = ChannelRepository::list(db.as_ref()).await.unwrap();
+        assert_eq!(kanaele.len(), vorher + 1);
+        assert!(kanaele.iter().any(|k| k.name == "Spaet"));
+    }
+
+    #[tokio::test]
+    async fn abgebrochenes_lesen_wird_gezaehlt() {
+        let (state, db) = state_mit_verzoegerung(Duration::from_millis(500)).await;
+        let session = session(&db).await;
+
+        // Client trennt die Verbindung: die Handler-Future wird verworfen
+        let anfrage = state.ausfuehren(kanal_liste(), session);
+        let _ = tokio::time::timeout(Duration::from_millis(10), anfrage).await;
+        assert_eq!(state.zeitlimit_statistik().abgebrochen_lesen, 1);
+    }
+
+    #[tokio::test]
+    async fn server_bearbeiten_wird_gespeichert() {
+        let (state, db) = state_mit_verzoegerung(Duration::ZERO).await;
+        let session = session(&db).await;
+
+        state
+            .ausfuehren(
+                Command::ServerEdit {
+                    name: Some("Umbenannt".into()),
+                    willkommensnachricht: Some("Hallo zusammen".into()),
+                    max_clients: Some(64),
+                    host_nachricht: None,
+                    afk_timeout_sek: None,
+                    afk_kanal_id: None,
+                    chat_bearbeitungsfrist_sek: None,
+                },
+                session.clone(),
+            )
+            .await
+            .unwrap();
+
+        let CmdResponse::ServerInfo(info) = state
+            .ausfuehren(Command::ServerInfo, session.clone())
+            .await
+            .unwrap()
+        else {
+            panic!("Erwartet ServerInfo");
+        };
+        assert_eq!(info.name, "Umbenannt");
+        assert_eq!(info.willkommensnachricht, "Hallo zusammen");
+        assert_eq!(info.max_clients, 64);
+        assert_eq!(info.host_nachricht, None);
+        assert_eq!(info.features, vec!["grpc".to_string()]);
+
+        let fehler = state
+            .ausfuehren(
+                Command::ServerEdit {
+                    name: None,
+                    willkommensnachricht: None,
+                    max_clients: Some(0),
+                    host_nachricht: None,
+                    afk_timeout_sek: None,
+                    afk_kanal_id: None,
+                    chat_bearbeitungsfrist_sek: None,
+                },
+                session,
+            )
+            .await
+            .unwrap_err();
+        assert_eq!(fehler.into_response().status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn motd_setzen_erhoeht_version_nur_bei_aenderung() {
+        let (state, db) = state_mit_verzoegerung(Duration::ZERO).await;
+        let session = session(&db).await;
+        let motd = |markdown: &str| Command::MotdSetzen {
+            markdown: markdown.into(),
+        };
+
+        for (text, version) in [("**Wartung**", 1), ("**Wartung**", 1), ("Neu", 2)] {
+            let CmdResponse::Motd(gespeichert) = state
+                .ausfuehren(motd(text), session.clone())
+                .await
+                .unwrap()
+            else {
+                panic!("Erwartet Motd");
+            };
+            assert_eq!(gespeichert.version, version);
+        }
+
+        let fehler = state
+            .ausfuehren(
+                motd(&"x".repeat(speakeasy_db::motd::MAX_MOTD_BYTES + 1)),
+                session.clone(),
+            )
+            .await
+            .unwrap_err();
+        assert_eq!(fehler.into_response().status(), StatusCode::BAD_REQUEST);
+
+        // API-Token ohne Admin-Scope: "cmd:*" reicht nicht
+        let token = CommanderSession {
+            scopes: vec!["cmd:*".into()],
+            auth_art: AuthArt::ApiToken,
+            ..session
+        };
+        let fehler = state.ausfuehren(motd("Fremd"), token).await.unwrap_err();
+        assert!(matches!(fehler, CommanderError::NichtAutorisiert(_)));
+    }
+
+    #[tokio::test]
+    async fn alarm_pruefung_verlangt_admin_scope() {
+        let (state, db) = state_mit_verzoegerung(Duration::ZERO).await;
+        let session = session(&db).await;
+
+        // Ohne Alarmierung (kein Server-Hook) nicht verfuegbar
+        let fehler = state
+            .ausfuehren(Command::AlertRegelnTesten, session.clone())
+            .await
+            .unwrap_err();
+        assert!(matches!(fehler, CommanderError::Intern(_)));
+
+        let token = CommanderSession {
+            scopes: vec!["cmd:*".into()],
+            auth_art: AuthArt::ApiToken,
+            ..session
+        };
+        let fehler = state
+            .ausfuehren(Command::AlertRegelnTesten, token)
+            .await
+            .unwrap_err();
+        assert!(matches!(fehler, CommanderError::NichtAutorisiert(_)));
+    }
+
+    #[tokio::test]
+    async fn konto_loeschen_prueft_ziel_und_scope() {
+        let (state, db) = state_mit_verzoegerung(Duration::ZERO).await;
+        let session = session(&db).await;
+
+        // Das eigene Konto nur im Client
+        let fehler = state
+            .ausfuehren(
+                Command::KontoLoeschen {
+                    benutzer_id: session.benutzer.id,
+                },
+                session.clone(),
+            )
+            .await
+            .unwrap_err();
+        assert_eq!(fehler.into_response().status(), StatusCode::BAD_REQUEST);
+
+        // Ohne Kontodienst nicht verfuegbar
+        let fehler = state
+            .ausfuehren(
+                Command::KontoExport {
+                    benutzer_id: Uuid::new_v4(),
+                },
+                session.clone(),
+            )
+            .await
+            .unwrap_err();
+        assert!(matches!(fehler, CommanderError::Intern(_)));
+
+        // API-Token ohne Admin-Scope: "cmd:*" reicht nicht
+        let token = CommanderSession {
+            scopes: vec!["cmd:*".into()],
+            auth_art: AuthArt::ApiToken,
+            ..session
+        };
+        let fehler = state
+            .ausfuehren(
+                Command::KontoLoeschen {
+                    benutzer_id: Uuid::new_v4(),
+                },
+                token,
+            )
+            .await
+            .unwrap_err();
+        assert!(matches!(fehler, CommanderError::NichtAutorisiert(_)));
+    }
+}
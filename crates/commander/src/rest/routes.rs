@@ -13,7 +13,20 @@ pub fn v1_router() -> Router<CommanderState> {
         // Server
         .route("/v1/server", get(handlers::server::get_server))
         .route("/v1/server", put(handlers::server::put_server))
+        .route(
+            "/v1/server/history",
+            get(handlers::server::get_server_history),
+        )
         .route("/v1/server/stop", post(handlers::server::post_server_stop))
+        .route(
+            "/v1/server/backup",
+            post(handlers::server::post_server_backup),
+        )
+        .route("/v1/server/motd", put(handlers::server::put_server_motd))
+        .route(
+            "/v1/server/alerts/test",
+            post(handlers::server::post_server_alerts_test),
+        )
         // Kanaele
         .route("/v1/channels", get(handlers::channels::list_channels))
         .route("/v1/channels", post(handlers::channels::create_channel))
@@ -22,12 +35,42 @@ pub fn v1_router() -> Router<CommanderState> {
             "/v1/channels/:id",
             delete(handlers::channels::delete_channel),
         )
+        .route(
+            "/v1/channels/:id/emergency-mute",
+            post(handlers::channels::emergency_mute_channel),
+        )
+        // Kanal-Vorlagen
+        .route(
+            "/v1/channel-templates",
+            get(handlers::channel_templates::list_templates)
+                .post(handlers::channel_templates::create_template),
+        )
+        .route(
+            "/v1/channel-templates/:id",
+            delete(handlers::channel_templates::delete_template),
+        )
+        .route(
+            "/v1/channel-templates/:id/instantiate",
+            post(handlers::channel_templates::instantiate_template),
+        )
         // Clients
         .route("/v1/clients", get(handlers::clients::list_clients))
         .route("/v1/clients/:id/kick", post(handlers::clients::kick_client))
         .route("/v1/clients/:id/ban", post(handlers::clients::ban_client))
         .route("/v1/clients/:id/move", post(handlers::clients::move_client))
         .route("/v1/clients/:id/poke", post(handlers::clients::poke_client))
+        .route(
+            "/v1/clients/:id/voice",
+            get(handlers::clients::voice_diagnose),
+        )
+        .route(
+            "/v1/channels/:id/move-clients",
+            post(handlers::clients::move_all_clients),
+        )
+        .route(
+            "/v1/channels/:id/speakers",
+            get(handlers::clients::list_speakers),
+        )
         // Berechtigungen
         .route(
             "/v1/permissions/:id",
@@ -38,11 +81,57 @@ pub fn v1_router() -> Router<CommanderState> {
             "/v1/permissions",
             post(handlers::permissions::set_permission),
         )
+        .route(
+            "/v1/users/:id/effective-permissions",
+            get(handlers::permissions::get_effective_permissions),
+        )
+        // Benutzerverwaltung
+        .route("/v1/users", get(handlers::users::list_users))
+        .route(
+            "/v1/users/:id/password",
+            put(handlers::users::set_user_password),
+        )
+        .route("/v1/users/:id/disable", post(handlers::users::disable_user))
+        .route("/v1/users/:id/remove", post(handlers::users::remove_user))
+        // Konten
+        .route("/v1/users/:id", delete(handlers::accounts::delete_account))
+        .route(
+            "/v1/users/:id/export",
+            post(handlers::accounts::export_account),
+        )
+        .route(
+            "/v1/users/:id/cleanup",
+            post(handlers::accounts::cleanup_account),
+        )
         // Dateien
+        .route(
+            "/v1/files/access-log",
+            get(handlers::files::get_file_access_log),
+        )
         .route(
             "/v1/files/:id",
             get(handlers::files::list_files).delete(handlers::files::delete_file),
         )
+        // Chat
+        .route("/v1/messages/:id", get(handlers::messages::get_message))
+        // Soundboard
+        .route(
+            "/v1/soundboard",
+            get(handlers::soundboard::list_sounds).post(handlers::soundboard::register_sound),
+        )
+        .route(
+            "/v1/soundboard/:id",
+            delete(handlers::soundboard::delete_sound),
+        )
         // Logs
         .route("/v1/logs", get(handlers::logs::get_logs))
+        // Zeitplaner
+        .route(
+            "/v1/schedules",
+            get(handlers::schedules::list_schedules).post(handlers::schedules::create_schedule),
+        )
+        .route(
+            "/v1/schedules/:id",
+            delete(handlers::schedules::cancel_schedule),
+        )
 }
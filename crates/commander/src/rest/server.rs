@@ -1,4 +1,11 @@
 //! Axum HTTP-Server fuer den Commander
+//!
+//! Ohne [`TlsKonfig`] laeuft der Server unverschluesselt (nur hinter einem
+//! Reverse-Proxy oder auf localhost sinnvoll). Mit TLS werden Verbindungen
+//! selbst angenommen, damit das Client-Zertifikat jeder Anfrage bekannt ist.
+//!
+//! Nach dem Shutdown-Signal werden keine Verbindungen mehr angenommen;
+//! laufende Anfragen werden noch beantwortet.
 
 use std::net::SocketAddr;
 use std::sync::Arc;
@@ -10,11 +17,20 @@ use axum::http::Request;
 use axum::http::{HeaderValue, Method, StatusCode};
 use axum::middleware::{self, Next};
 use axum::response::{IntoResponse, Json, Response};
+use axum::Router;
+use hyper::body::Incoming;
+use hyper_util::rt::{TokioExecutor, TokioIo};
+use hyper_util::server::conn::auto;
+use tokio::net::TcpListener;
+use tokio::sync::{mpsc, watch};
+use tokio::task::JoinSet;
+use tower::ServiceExt;
 use tower_http::cors::CorsLayer;
 use tower_http::trace::TraceLayer;
 
 use crate::rate_limit::RateLimiter;
-use crate::rest::{routes::v1_router, CommanderState};
+use crate::rest::{routes::v1_router, CommanderState, CLIENT_IDENTITAET};
+use crate::tls::{self, TlsKonfig, TlsQuelle};
 
 /// REST-Server-Konfiguration
 #[derive(Debug, Clone)]
@@ -22,6 +38,8 @@ pub struct RestServerKonfig {
     pub bind_addr: SocketAddr,
     /// Erlaubte CORS-Origins. Leer = alle Origins erlaubt (nur fuer Entwicklung).
     pub cors_origins: Vec<String>,
+    /// TLS (optional mit Client-Zertifikaten); `None` = unverschluesselt
+    pub tls: Option<TlsKonfig>,
 }
 
 impl Default for RestServerKonfig {
@@ -29,6 +47,7 @@ impl Default for RestServerKonfig {
         Self {
             bind_addr: "127.0.0.1:9300".parse().unwrap(),
             cors_origins: vec![],
+            tls: None,
         }
     }
 }
@@ -73,18 +92,32 @@ async fn rate_limit_middleware(
 /// Axum HTTP-Server fuer den Commander
 pub struct RestServer {
     konfig: RestServerKonfig,
+    bereit: Option<Box<dyn FnOnce(SocketAddr) + Send>>,
 }
 
 impl RestServer {
     pub fn neu(konfig: RestServerKonfig) -> Self {
-        Self { konfig }
+        Self {
+            konfig,
+            bereit: None,
+        }
+    }
+
+    /// Meldet die gebundene Adresse, sobald der Listener Anfragen annimmt
+    pub fn bei_bereitschaft(mut self, melden: impl FnOnce(SocketAddr) + Send + 'static) -> Self {
+        self.bereit = Some(Box::new(melden));
+        self
     }
 
     /// Startet den REST-Server mit dem gegebenen State und Rate Limiter
+    ///
+    /// Kehrt zurueck, sobald `shutdown_rx` `true` meldet (oder der Sender
+    /// verworfen wird) und alle laufenden Anfragen beantwortet sind.
     pub async fn starten(
-        self,
+        mut self,
         state: CommanderState,
         rate_limiter: Arc<RateLimiter>,
+        shutdown_rx: watch::Receiver<bool>,
     ) -> Result<()> {
         // CORS konfigurieren: entweder spezifische Origins oder Any
         let cors = if self.konfig.cors_origins.is_empty() {
@@ -119,15 +152,329 @@ impl RestServer {
             .layer(cors)
             .with_state(state);
 
-        let listener = tokio::net::TcpListener::bind(self.konfig.bind_addr).await?;
-        tracing::info!(addr = %self.konfig.bind_addr, "REST-Commander-Server gestartet");
+        // Zertifikate vor dem Binden laden: Fehler verhindern den Start
+        let quelle = match self.konfig.tls.take() {
+            Some(tls) => Some(Arc::new(TlsQuelle::laden(tls, &[b"h2", b"http/1.1"])?)),
+            None => None,
+        };
+
+        let listener = TcpListener::bind(self.konfig.bind_addr).await?;
+        tracing::info!(
+            addr = %self.konfig.bind_addr,
+            tls = quelle.is_some(),
+            mtls = quelle.as_ref().is_some_and(|q| q.mit_client_zertifikaten()),
+            "REST-Commander-Server gestartet"
+        );
+        if let Some(melden) = self.bereit.take() {
+            melden(listener.local_addr()?);
+        }
 
-        axum::serve(listener, app).await?;
+        match quelle {
+            Some(quelle) => ueber_tls_bedienen(listener, app, quelle, shutdown_rx).await?,
+            None => {
+                axum::serve(listener, app)
+                    .with_graceful_shutdown(beendet(shutdown_rx))
+                    .await?
+            }
+        }
+        tracing::info!("REST-Commander-Server beendet");
         Ok(())
     }
 }
 
+/// Wartet auf das Shutdown-Signal (ein verworfener Sender zaehlt als Signal)
+async fn beendet(mut shutdown_rx: watch::Receiver<bool>) {
+    let _ = shutdown_rx.wait_for(|beenden| *beenden).await;
+}
+
+/// Bedient Anfragen ueber TLS
+///
+/// Jede Anfrage laeuft im Kontext des Client-Zertifikats ihrer Verbindung
+/// ([`CLIENT_IDENTITAET`]).
+async fn ueber_tls_bedienen(
+    listener: TcpListener,
+    app: Router,
+    quelle: Arc<TlsQuelle>,
+    shutdown_rx: watch::Receiver<bool>,
+) -> Result<()> {
+    let nachladen = tokio::spawn(Arc::clone(&quelle).beobachten());
+    let (tx, mut rx) = mpsc::channel(64);
+    let annahme = tokio::spawn(tls::annehmen(listener, quelle, tx));
+    let mut verbindungen = JoinSet::new();
+
+    loop {
+        let verbindung = tokio::select! {
+            verbindung = rx.recv() => verbindung,
+            _ = beendet(shutdown_rx.clone()) => None,
+        };
+        let Some(verbindung) = verbindung else {
+            break;
+        };
+        // Beendete Verbindungen einsammeln
+        while verbindungen.try_join_next().is_some() {}
+
+        let app = app.clone();
+        let identitaet = verbindung.identitaet;
+        let peer = verbindung.peer;
+        let shutdown_rx = shutdown_rx.clone();
+        verbindungen.spawn(async move {
+            let dienst = hyper::service::service_fn(move |anfrage: Request<Incoming>| {
+                CLIENT_IDENTITAET.scope(identitaet.clone(), app.clone().oneshot(anfrage))
+            });
+            let builder = auto::Builder::new(TokioExecutor::new());
+            let verbindung =
+                builder.serve_connection_with_upgrades(TokioIo::new(verbindung.stream), dienst);
+            tokio::pin!(verbindung);
+            let ergebnis = tokio::select! {
+                ergebnis = verbindung.as_mut() => ergebnis,
+                _ = beendet(shutdown_rx) => {
+                    // Laufende Anfragen noch beantworten, dann schliessen
+                    verbindung.as_mut().graceful_shutdown();
+                    verbindung.await
+                }
+            };
+            if let Err(e) = ergebnis {
+                tracing::debug!(peer = %peer, fehler = %e, "REST-Verbindung abgebrochen");
+            }
+        });
+    }
+
+    nachladen.abort();
+    annahme.abort();
+    while verbindungen.join_next().await.is_some() {}
+    match annahme.await {
+        Ok(ergebnis) => Ok(ergebnis?),
+        Err(e) if e.is_cancelled() => Ok(()),
+        Err(e) => Err(e.into()),
+    }
+}
+
 /// GET /health – Health-Check-Endpunkt
 pub async fn health() -> impl IntoResponse {
     (StatusCode::OK, Json(serde_json::json!({ "status": "ok" })))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::{CommanderAuth, ZertifikatsZuordnung};
+    use crate::commands::CommandExecutor;
+    use crate::error::CommanderError;
+    use crate::rate_limit::RateLimitKonfig;
+    use crate::rest::{ExecutorFn, TokenValidatorFn, ZertifikatsValidatorFn};
+    use crate::tls::tests::TestPki;
+    use crate::tls::ClientZertModus;
+    use rustls::pki_types::{CertificateDer, PrivateKeyDer, ServerName};
+    use rustls::{ClientConfig, RootCertStore};
+    use speakeasy_auth::{ApiTokenStore, AuthService, BanService, PermissionService, SessionStore};
+    use speakeasy_db::{einstellungen::ServerEinstellungen, models::KanalbaumGrenzen, SqliteDb};
+    use std::time::Duration;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpStream;
+    use tokio_rustls::TlsConnector;
+
+    /// Commander mit echtem Executor; Zertifikate fuer "ops.example.org"
+    /// duerfen nur die Server-Info lesen. Jeder Befehl wartet `verzoegerung`.
+    async fn state(verzoegerung: Duration) -> CommanderState {
+        let db = Arc::new(SqliteDb::in_memory().await.unwrap());
+        let auth_service = Arc::new(AuthService::neu(
+            Arc::clone(&db),
+            SessionStore::neu(),
+            ApiTokenStore::neu(),
+        ));
+        auth_service
+            .registrieren("operator", "sicheres-passwort-123")
+            .await
+            .unwrap();
+        let executor = CommandExecutor::neu(
+            Arc::clone(&db),
+            Arc::clone(&db),
+            Arc::clone(&db),
+            Arc::clone(&db),
+            Arc::clone(&db),
+            Arc::clone(&db),
+            Arc::clone(&db),
+            Arc::clone(&db),
+            Arc::clone(&db),
+            Arc::clone(&auth_service),
+            PermissionService::neu(Arc::clone(&db)),
+            BanService::neu(Arc::clone(&db)),
+            ServerEinstellungen {
+                name: "mTLS".into(),
+                willkommensnachricht: None,
+                max_clients: 32,
+                host_nachricht: None,
+                chat_bearbeitungsfrist_sek: 0,
+            },
+            "0.0.0".into(),
+            KanalbaumGrenzen::default(),
+        );
+        let executor_fn: ExecutorFn = Arc::new(move |cmd, session| {
+            let exec = Arc::clone(&executor);
+            Box::pin(async move {
+                tokio::time::sleep(verzoegerung).await;
+                exec.ausfuehren(cmd, &session).await
+            })
+        });
+        let token_validator: TokenValidatorFn =
+            Arc::new(|_| Err(CommanderError::Authentifizierung("kein Token".into())));
+
+        let auth = Arc::new(CommanderAuth::neu(auth_service));
+        let zuordnungen = vec![ZertifikatsZuordnung {
+            name: "ops.example.org".into(),
+            benutzer: "operator".into(),
+            scopes: vec!["cmd:serverinfo".into()],
+        }];
+        let zertifikats_validator: ZertifikatsValidatorFn = Arc::new(move |identitaet| {
+            tokio::task::block_in_place(|| {
+                tokio::runtime::Handle::current()
+                    .block_on(auth.zertifikat_validieren(identitaet, &zuordnungen))
+            })
+        });
+        CommanderState::neu(executor_fn, token_validator)
+            .mit_zertifikats_validator(zertifikats_validator)
+    }
+
+    /// Laufender Server: Adresse, Shutdown-Sender und Task
+    async fn starten(
+        pki: &TestPki,
+        modus: ClientZertModus,
+        verzoegerung: Duration,
+    ) -> (SocketAddr, watch::Sender<bool>, tokio::task::JoinHandle<()>) {
+        let konfig = RestServerKonfig {
+            bind_addr: "127.0.0.1:0".parse().unwrap(),
+            cors_origins: vec![],
+            tls: Some(pki.server_konfig().mit_client_ca(pki.ca_pfad(), modus)),
+        };
+        let (tx, rx) = tokio::sync::oneshot::channel();
+        let server = RestServer::neu(konfig).bei_bereitschaft(move |addr| {
+            let _ = tx.send(addr);
+        });
+        let state = state(verzoegerung).await;
+        let (shutdown_tx, shutdown_rx) = watch::channel(false);
+        let handle = tokio::spawn(async move {
+            server
+                .starten(
+                    state,
+                    RateLimiter::neu(RateLimitKonfig::default()),
+                    shutdown_rx,
+                )
+                .await
+                .unwrap();
+        });
+        (rx.await.unwrap(), shutdown_tx, handle)
+    }
+
+    /// Sendet ein GET und gibt den Statuscode zurueck (`None` = Verbindung abgelehnt)
+    async fn get(
+        pki: &TestPki,
+        addr: SocketAddr,
+        pfad: &str,
+        client: Option<(&str, &[&str])>,
+    ) -> Option<u16> {
+        let mut wurzeln = RootCertStore::empty();
+        let ca = std::fs::read(pki.ca_pfad()).unwrap();
+        for zertifikat in rustls_pemfile::certs(&mut ca.as_slice()) {
+            wurzeln.add(zertifikat.unwrap()).unwrap();
+        }
+        let builder =
+            ClientConfig::builder_with_provider(Arc::new(rustls::crypto::ring::default_provider()))
+                .with_safe_default_protocol_versions()
+                .unwrap()
+                .with_root_certificates(wurzeln);
+        let config = match client {
+            Some((cn, san)) => {
+                let (zertifikat, schluessel) = pki.ausstellen(cn, san);
+                let kette: Vec<CertificateDer<'static>> =
+                    rustls_pemfile::certs(&mut zertifikat.as_bytes())
+                        .collect::<Result<_, _>>()
+                        .unwrap();
+                let schluessel: PrivateKeyDer<'static> =
+                    rustls_pemfile::private_key(&mut schluessel.as_bytes())
+                        .unwrap()
+                        .unwrap();
+                builder.with_client_auth_cert(kette, schluessel).unwrap()
+            }
+            None => builder.with_no_client_auth(),
+        };
+
+        let tcp = TcpStream::connect(addr).await.ok()?;
+        let mut stream = TlsConnector::from(Arc::new(config))
+            .connect(ServerName::try_from("localhost").unwrap(), tcp)
+            .await
+            .ok()?;
+        let anfrage =
+            format!("GET {pfad} HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n");
+        stream.write_all(anfrage.as_bytes()).await.ok()?;
+        let mut antwort = String::new();
+        stream.read_to_string(&mut antwort).await.ok()?;
+        antwort.split_whitespace().nth(1)?.parse().ok()
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn pflicht_modus_lehnt_ohne_client_zertifikat_ab() {
+        let pki = TestPki::neu();
+        let (addr, _shutdown, _) = starten(&pki, ClientZertModus::Pflicht, Duration::ZERO).await;
+
+        assert_eq!(get(&pki, addr, "/v1/server", None).await, None);
+        assert_eq!(
+            get(
+                &pki,
+                addr,
+                "/v1/server",
+                Some(("operator-1", &["ops.example.org"]))
+            )
+            .await,
+            Some(200)
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn zugeordnete_scopes_gelten_wie_bei_api_tokens() {
+        let pki = TestPki::neu();
+        let (addr, _shutdown, _) = starten(&pki, ClientZertModus::Pflicht, Duration::ZERO).await;
+        let operator = Some(("operator-1", &["ops.example.org"][..]));
+
+        assert_eq!(get(&pki, addr, "/v1/server", operator).await, Some(200));
+        assert_eq!(get(&pki, addr, "/v1/channels", operator).await, Some(403));
+        // Gueltiges, aber nicht zugeordnetes Zertifikat
+        assert_eq!(
+            get(
+                &pki,
+                addr,
+                "/v1/server",
+                Some(("fremd", &["fremd.example.org"]))
+            )
+            .await,
+            Some(401)
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn optionaler_modus_verlangt_dann_einen_token() {
+        let pki = TestPki::neu();
+        let (addr, _shutdown, _) = starten(&pki, ClientZertModus::Optional, Duration::ZERO).await;
+
+        assert_eq!(get(&pki, addr, "/v1/server", None).await, Some(401));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn laufende_anfrage_wird_beim_shutdown_beantwortet() {
+        let pki = TestPki::neu();
+        let (addr, shutdown, handle) =
+            starten(&pki, ClientZertModus::Pflicht, Duration::from_millis(500)).await;
+        let operator = Some(("operator-1", &["ops.example.org"][..]));
+
+        let (status, ()) = tokio::join!(get(&pki, addr, "/v1/server", operator), async {
+            tokio::time::sleep(Duration::from_millis(200)).await;
+            shutdown.send(true).unwrap();
+        });
+        assert_eq!(status, Some(200));
+
+        tokio::time::timeout(Duration::from_secs(5), handle)
+            .await
+            .expect("Server nicht beendet")
+            .unwrap();
+        assert_eq!(get(&pki, addr, "/v1/server", operator).await, None);
+    }
+}
@@ -0,0 +1,306 @@
+//! Anfrage- und Antworttypen der REST-API
+//!
+//! Die Handler und `speakeasy-commander-client` verwenden dieselben Typen,
+//! damit Server und Client nicht auseinanderlaufen. Antworten tragen die
+//! Nutzlast des Befehls ohne `typ`-Markierung ([`Response::nutzlast`]).
+//!
+//! [`Response::nutzlast`]: crate::commands::types::Response::nutzlast
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use speakeasy_db::models::GeplanteAktion;
+use uuid::Uuid;
+
+pub use speakeasy_db::motd::Motd;
+pub use speakeasy_observability::alarm::AlarmStatus;
+
+pub use crate::commands::types::{
+    BenutzerInfo, BenutzerSeite, BerechtigungsEintrag, BerechtigungsRegel, BerechtigungsWertInput,
+    BereinigungsErgebnis, ClientInfo, ClientSeite, DateiEintrag, DateiZugriffEintrag,
+    DateiZugriffSeite, EffektiverBerechtigungsEintrag, KanalBereinigungInfo, KanalInfo, KanalSeite,
+    KontoExportErgebnis, KontoLoeschErgebnis, LogEintrag, NotfallStummErgebnis,
+    SammelVerschiebungErgebnis, SeitenInfo, ServerInfoResponse, ServerStartInfo, SoundInfo,
+    UebersprungenerClient, VorlageInfo, ZeitplanInfo,
+};
+
+// ---------------------------------------------------------------------------
+// Server
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ServerBearbeitenBody {
+    pub name: Option<String>,
+    pub willkommensnachricht: Option<String>,
+    pub max_clients: Option<u32>,
+    pub host_nachricht: Option<String>,
+    pub afk_timeout_sek: Option<u32>,
+    pub afk_kanal_id: Option<Uuid>,
+    /// Standard-Frist fuer das Bearbeiten eigener Chat-Nachrichten (0 = unbegrenzt)
+    #[serde(default)]
+    pub chat_bearbeitungsfrist_sek: Option<u32>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ServerStoppenBody {
+    pub grund: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupBody {
+    /// Zielverzeichnis auf dem Server (darf nicht existieren oder muss leer sein)
+    pub ziel_pfad: String,
+    #[serde(default = "standard_include_files")]
+    pub include_files: bool,
+}
+
+fn standard_include_files() -> bool {
+    true
+}
+
+/// Filter fuer `GET /v1/server/history` (neueste Starts zuerst)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct HistorieQuery {
+    pub limit: Option<u32>,
+}
+
+/// Neue Nachricht des Tages (`PUT /v1/server/motd`, leer = entfernen)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MotdBody {
+    pub markdown: String,
+}
+
+/// Antwort auf `POST /v1/server/backup` (Fortschritt folgt als Ereignis)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupGestartet {
+    pub ziel_pfad: String,
+}
+
+// ---------------------------------------------------------------------------
+// Kanaele und Vorlagen
+// ---------------------------------------------------------------------------
+
+/// Filter und Seite fuer `GET /v1/channels` (Standard: Seite 1 mit 50 Kanaelen)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct KanalQuery {
+    pub page: Option<u32>,
+    pub page_size: Option<u32>,
+    /// Teil des Kanalnamens (Gross-/Kleinschreibung egal)
+    pub name: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct KanalErstellenBody {
+    pub name: String,
+    pub parent_id: Option<Uuid>,
+    pub thema: Option<String>,
+    pub passwort: Option<String>,
+    pub max_clients: Option<i64>,
+    pub sort_order: Option<i64>,
+    pub permanent: Option<bool>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct KanalBearbeitenBody {
+    pub name: Option<String>,
+    pub thema: Option<String>,
+    pub max_clients: Option<i64>,
+    pub sort_order: Option<i64>,
+    /// Langsam-Modus in Sekunden (0 = aus)
+    #[serde(default)]
+    pub langsammodus_sek: Option<u32>,
+    /// Bearbeitungsfrist in Sekunden (0 = unbegrenzt)
+    #[serde(default)]
+    pub bearbeitungsfrist_sek: Option<u32>,
+    /// Eigene Bearbeitungsfrist entfernen, es gilt wieder der Server-Standard
+    #[serde(default)]
+    pub bearbeitungsfrist_standard: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NotfallStummBody {
+    pub aktivieren: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VorlageErstellenBody {
+    pub name: String,
+    pub beschreibung: Option<String>,
+    /// Wurzelknoten des Kanal-Teilbaums
+    pub definition: serde_json::Value,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct InstanziierenBody {
+    pub parent_id: Option<Uuid>,
+    pub name_prefix: Option<String>,
+}
+
+// ---------------------------------------------------------------------------
+// Clients
+/// Rumpf von `POST /v1/users/:id/cleanup`
+///
+/// Mit `job_id` wird ein unterbrochener Lauf fortgesetzt, `zeitraum_secs`
+/// und `kanal_id` werden dann ignoriert.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BereinigungBody {
+    pub zeitraum_secs: Option<u64>,
+    pub kanal_id: Option<Uuid>,
+    pub job_id: Option<Uuid>,
+}
+
+// ---------------------------------------------------------------------------
+// Benutzerverwaltung
+
+/// Filter und Seite fuer `GET /v1/users` (Standard: 50 Benutzer ab dem ersten)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BenutzerQuery {
+    /// Teil des Benutzernamens (Gross-/Kleinschreibung egal)
+    pub filter: Option<String>,
+    pub limit: Option<u32>,
+    pub offset: Option<u32>,
+}
+
+/// Rumpf von `PUT /v1/users/:id/password`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PasswortSetzenBody {
+    pub neues_passwort: String,
+}
+
+/// Rumpf von `POST /v1/users/:id/disable` (`false` = wieder freischalten)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeaktivierenBody {
+    #[serde(default = "standard_deaktiviert")]
+    pub deaktiviert: bool,
+}
+
+fn standard_deaktiviert() -> bool {
+    true
+}
+
+// ---------------------------------------------------------------------------
+
+/// Filter und Seite fuer `GET /v1/clients` (Standard: Seite 1 mit 50 Clients)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ClientQuery {
+    pub page: Option<u32>,
+    pub page_size: Option<u32>,
+    pub channel_id: Option<Uuid>,
+    /// Teil des Benutzernamens (Gross-/Kleinschreibung egal)
+    pub username: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct KickBody {
+    pub grund: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BanBody {
+    pub grund: Option<String>,
+    pub dauer_secs: Option<u64>,
+    pub ip_bannen: Option<bool>,
+    /// Nachrichten und Dateien der letzten N Sekunden mitentfernen
+    #[serde(default)]
+    pub inhalte_entfernen_secs: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MoveBody {
+    pub kanal_id: Uuid,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MoveAllBody {
+    pub nach_kanal_id: Uuid,
+    /// Nur diese Benutzer verschieben (fehlt = alle im Kanal)
+    pub nur_user_ids: Option<Vec<Uuid>>,
+    /// Bei zu wenig Platz im Ziel teilweise verschieben statt abzulehnen
+    #[serde(default)]
+    pub teilweise: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PokeBody {
+    pub nachricht: String,
+}
+
+// ---------------------------------------------------------------------------
+// Berechtigungen
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EffektivQuery {
+    /// Kanal, fuer den aufgeloest wird (ohne: Server-Root)
+    pub channel: Option<Uuid>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SetPermissionBody {
+    pub ziel: String,
+    pub permission: String,
+    pub wert: BerechtigungsWertInput,
+    pub scope: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RemovePermissionBody {
+    pub ziel: String,
+    pub scope: Option<String>,
+}
+
+// ---------------------------------------------------------------------------
+// Dateien und Logs
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ZugriffsQuery {
+    pub datei_id: Option<Uuid>,
+    pub benutzer_id: Option<Uuid>,
+    pub von: Option<DateTime<Utc>>,
+    pub bis: Option<DateTime<Utc>>,
+    pub limit: Option<u32>,
+    pub offset: Option<u32>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LogQuery {
+    pub limit: Option<u32>,
+    pub offset: Option<u32>,
+    pub aktion: Option<String>,
+}
+
+// ---------------------------------------------------------------------------
+// Soundboard
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SoundQuery {
+    /// Kanal, fuer den gelistet wird (ohne: alle Sounds)
+    pub channel: Option<Uuid>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SoundRegistrierenBody {
+    pub name: String,
+    /// Hochgeladene Audiodatei (WAV)
+    pub datei_id: Uuid,
+    /// Nur in diesem Kanal abspielbar (fehlt = serverweit)
+    pub kanal_id: Option<Uuid>,
+}
+
+// ---------------------------------------------------------------------------
+// Zeitplaner
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ZeitplanErstellenBody {
+    pub name: String,
+    /// z.B. `{"type": "kanal_aus_vorlage", "vorlage_id": "...", "loeschen_nach_min": 180}`
+    pub aktion: GeplanteAktion,
+    /// Einmaliger Zeitpunkt (RFC 3339)
+    pub einmalig: Option<DateTime<Utc>>,
+    /// Cron-Ausdruck mit fuenf Feldern
+    pub cron: Option<String>,
+    /// IANA-Zeitzone fuer `cron`, z.B. `Europe/Berlin` (Standard: UTC)
+    pub zeitzone: Option<String>,
+}
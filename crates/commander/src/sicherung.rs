@@ -0,0 +1,652 @@
+//! Sicherung und Wiederherstellung von Datenbank, Dateien und Konfiguration
+//!
+//! Eine Sicherung ist ein Verzeichnis mit festem Aufbau:
+//!
+//! ```text
+//! <ziel>/
+//!   manifest.json   Versionen sowie Groesse und SHA-256 jeder Datei
+//!   speakeasy.db    konsistente Kopie der Datenbank (`VACUUM INTO`)
+//!   config.toml     effektive Konfiguration zum Zeitpunkt der Sicherung
+//!   dateien/        Datei-Speicher mit unveraendertem Aufbau
+//! ```
+//!
+//! Die Datenbank wird im laufenden Betrieb kopiert, danach der Datei-Speicher.
+//! Dateien werden per Hardlink uebernommen (Kopie, wenn Quelle und Ziel auf
+//! verschiedenen Dateisystemen liegen) – der Speicher legt Dateien nur an und
+//! loescht sie, veraendert sie aber nie. Waehrend der Sicherung hochgeladene
+//! Dateien koennen ohne Datenbank-Eintrag enthalten sein.
+//!
+//! Die Wiederherstellung ([`wiederherstellen`]) laeuft offline: sie prueft
+//! Manifest, Pruefsummen und Schema-Version gegen dieses Binary und schreibt
+//! nur in ein leeres bzw. noch nicht vorhandenes Verzeichnis.
+
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use speakeasy_db::SqliteDb;
+
+use crate::error::{CommanderError, CommanderResult};
+
+/// Version des Sicherungsformats
+pub const FORMAT_VERSION: u32 = 1;
+
+pub const MANIFEST_DATEI: &str = "manifest.json";
+pub const DATENBANK_DATEI: &str = "speakeasy.db";
+pub const CONFIG_DATEI: &str = "config.toml";
+pub const DATEIEN_VERZEICHNIS: &str = "dateien";
+
+/// Inhaltsverzeichnis einer Sicherung
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SicherungsManifest {
+    pub format: u32,
+    pub server_version: String,
+    /// Schema-Version der gesicherten Datenbank
+    pub schema_version: i64,
+    pub erstellt_am: DateTime<Utc>,
+    pub dateien_enthalten: bool,
+    pub eintraege: Vec<ManifestEintrag>,
+}
+
+/// Eine Datei der Sicherung (Pfad relativ zum Sicherungsverzeichnis)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ManifestEintrag {
+    /// Mit `/` getrennt, z.B. `dateien/<kanal>/<datei>`
+    pub pfad: String,
+    pub groesse: u64,
+    pub sha256: String,
+}
+
+/// Was gesichert wird (vom Server beim Start gesetzt)
+#[derive(Debug, Clone)]
+pub struct SicherungsQuellen {
+    pub db: SqliteDb,
+    /// Wurzel des Datei-Speichers
+    pub dateien: PathBuf,
+    /// Effektive Konfiguration als TOML
+    pub config: Option<String>,
+    pub server_version: String,
+}
+
+/// Empfaenger der Fortschrittsmeldungen
+pub type FortschrittFn = Arc<dyn Fn(SicherungsFortschritt) + Send + Sync>;
+
+/// Auftrag fuer eine Sicherung
+#[derive(Clone)]
+pub struct SicherungsAuftrag {
+    pub ziel: PathBuf,
+    /// Datei-Speicher mitsichern
+    pub dateien_einschliessen: bool,
+    pub fortschritt: FortschrittFn,
+}
+
+/// Abschnitt einer laufenden Sicherung
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SicherungsPhase {
+    Datenbank,
+    Dateien,
+    Pruefsummen,
+    Abgeschlossen,
+    Fehlgeschlagen(String),
+}
+
+/// Fortschrittsmeldung einer Sicherung
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SicherungsFortschritt {
+    pub ziel: String,
+    pub phase: SicherungsPhase,
+    /// Erledigte Schritte der Phase
+    pub aktuell: u64,
+    pub gesamt: u64,
+}
+
+/// Ergebnis einer Sicherung bzw. Wiederherstellung
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SicherungsBericht {
+    pub pfad: String,
+    pub server_version: String,
+    pub schema_version: i64,
+    /// Anzahl Dateien aus dem Datei-Speicher
+    pub dateien: usize,
+    /// Gesamtgroesse aller Eintraege
+    pub bytes: u64,
+}
+
+impl SicherungsBericht {
+    fn aus_manifest(pfad: &Path, manifest: &SicherungsManifest) -> Self {
+        let prefix = format!("{DATEIEN_VERZEICHNIS}/");
+        Self {
+            pfad: pfad.display().to_string(),
+            server_version: manifest.server_version.clone(),
+            schema_version: manifest.schema_version,
+            dateien: manifest
+                .eintraege
+                .iter()
+                .filter(|e| e.pfad.starts_with(&prefix))
+                .count(),
+            bytes: manifest.eintraege.iter().map(|e| e.groesse).sum(),
+        }
+    }
+}
+
+/// Lehnt ein Ziel ab, das existiert und nicht leer ist
+pub async fn ziel_pruefen(ziel: &Path) -> CommanderResult<()> {
+    match tokio::fs::read_dir(ziel).await {
+        Ok(mut eintraege) => {
+            if eintraege.next_entry().await?.is_some() {
+                return Err(CommanderError::UngueltigeEingabe(format!(
+                    "Ziel '{}' ist nicht leer",
+                    ziel.display()
+                )));
+            }
+            Ok(())
+        }
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        Err(_) if ziel.exists() => Err(CommanderError::UngueltigeEingabe(format!(
+            "Ziel '{}' ist kein Verzeichnis",
+            ziel.display()
+        ))),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Erstellt eine Sicherung und meldet den Fortschritt
+///
+/// Schlaegt sie fehl, wird zuletzt [`SicherungsPhase::Fehlgeschlagen`]
+/// gemeldet; bereits geschriebene Teile bleiben im Ziel liegen.
+pub async fn erstellen(
+    quellen: &SicherungsQuellen,
+    auftrag: SicherungsAuftrag,
+) -> CommanderResult<SicherungsBericht> {
+    let ergebnis = erstellen_intern(quellen, &auftrag).await;
+    if let Err(e) = &ergebnis {
+        melden(
+            &auftrag,
+            SicherungsPhase::Fehlgeschlagen(e.to_string()),
+            0,
+            0,
+        );
+    }
+    ergebnis
+}
+
+fn melden(auftrag: &SicherungsAuftrag, phase: SicherungsPhase, aktuell: u64, gesamt: u64) {
+    (auftrag.fortschritt)(SicherungsFortschritt {
+        ziel: auftrag.ziel.display().to_string(),
+        phase,
+        aktuell,
+        gesamt,
+    });
+}
+
+/// Hoechstens etwa 20 Meldungen pro Phase – der Ereignis-Kanal des
+/// Commanders ist begrenzt und wird auch fuer Client-Broadcasts genutzt
+fn ist_meldeschritt(aktuell: u64, gesamt: u64) -> bool {
+    aktuell == gesamt || aktuell.is_multiple_of((gesamt / 20).max(1))
+}
+
+async fn erstellen_intern(
+    quellen: &SicherungsQuellen,
+    auftrag: &SicherungsAuftrag,
+) -> CommanderResult<SicherungsBericht> {
+    let ziel = auftrag.ziel.as_path();
+    ziel_pruefen(ziel).await?;
+    tokio::fs::create_dir_all(ziel).await?;
+
+    melden(auftrag, SicherungsPhase::Datenbank, 0, 1);
+    quellen.db.sichern_nach(&ziel.join(DATENBANK_DATEI)).await?;
+    let schema_version = quellen.db.schema_version().await?;
+    melden(auftrag, SicherungsPhase::Datenbank, 1, 1);
+
+    let mut pfade = vec![DATENBANK_DATEI.to_string()];
+    if let Some(config) = &quellen.config {
+        tokio::fs::write(ziel.join(CONFIG_DATEI), config).await?;
+        pfade.push(CONFIG_DATEI.to_string());
+    }
+
+    if auftrag.dateien_einschliessen {
+        let dateien = dateien_auflisten(&quellen.dateien).await?;
+        let gesamt = dateien.len() as u64;
+        for (index, relativ) in dateien.into_iter().enumerate() {
+            let pfad = format!("{DATEIEN_VERZEICHNIS}/{relativ}");
+            verknuepfen_oder_kopieren(
+                &pfad_aufloesen(&quellen.dateien, &relativ)?,
+                &pfad_aufloesen(ziel, &pfad)?,
+            )
+            .await?;
+            pfade.push(pfad);
+            if ist_meldeschritt(index as u64 + 1, gesamt) {
+                melden(auftrag, SicherungsPhase::Dateien, index as u64 + 1, gesamt);
+            }
+        }
+    }
+
+    // Pruefsummen ueber die geschriebenen Kopien
+    let gesamt = pfade.len() as u64;
+    let mut eintraege = Vec::with_capacity(pfade.len());
+    for (index, pfad) in pfade.into_iter().enumerate() {
+        let (groesse, sha256) = pruefsumme(pfad_aufloesen(ziel, &pfad)?).await?;
+        eintraege.push(ManifestEintrag {
+            pfad,
+            groesse,
+            sha256,
+        });
+        if ist_meldeschritt(index as u64 + 1, gesamt) {
+            melden(
+                auftrag,
+                SicherungsPhase::Pruefsummen,
+                index as u64 + 1,
+                gesamt,
+            );
+        }
+    }
+
+    let manifest = SicherungsManifest {
+        format: FORMAT_VERSION,
+        server_version: quellen.server_version.clone(),
+        schema_version,
+        erstellt_am: Utc::now(),
+        dateien_enthalten: auftrag.dateien_einschliessen,
+        eintraege,
+    };
+    let json = serde_json::to_vec_pretty(&manifest).map_err(anyhow::Error::from)?;
+    tokio::fs::write(ziel.join(MANIFEST_DATEI), json).await?;
+
+    let bericht = SicherungsBericht::aus_manifest(ziel, &manifest);
+    tracing::info!(
+        ziel = %bericht.pfad,
+        dateien = bericht.dateien,
+        bytes = bericht.bytes,
+        "Sicherung erstellt"
+    );
+    melden(auftrag, SicherungsPhase::Abgeschlossen, gesamt, gesamt);
+    Ok(bericht)
+}
+
+/// Liest das Manifest einer Sicherung und prueft sie vollstaendig
+///
+/// Abgelehnt werden unbekannte Formate, ein neueres Schema als das dieses
+/// Binarys sowie fehlende oder veraenderte Dateien.
+pub async fn pruefen(archiv: &Path) -> CommanderResult<SicherungsManifest> {
+    let ungueltig = |grund: String| {
+        CommanderError::UngueltigeEingabe(format!(
+            "Sicherung '{}' ungueltig: {grund}",
+            archiv.display()
+        ))
+    };
+
+    let json = tokio::fs::read(archiv.join(MANIFEST_DATEI))
+        .await
+        .map_err(|e| ungueltig(format!("{MANIFEST_DATEI} nicht lesbar ({e})")))?;
+    let manifest: SicherungsManifest =
+        serde_json::from_slice(&json).map_err(|e| ungueltig(e.to_string()))?;
+
+    if manifest.format > FORMAT_VERSION {
+        return Err(ungueltig(format!(
+            "Format {} wird nicht unterstuetzt (bis {FORMAT_VERSION})",
+            manifest.format
+        )));
+    }
+    let unterstuetzt = SqliteDb::schema_version_binary();
+    if manifest.schema_version > unterstuetzt {
+        return Err(ungueltig(format!(
+            "Schema-Version {} ist neuer als die dieses Servers ({unterstuetzt}); \
+             Wiederherstellung erst mit Server {} oder neuer",
+            manifest.schema_version, manifest.server_version
+        )));
+    }
+    if !manifest.eintraege.iter().any(|e| e.pfad == DATENBANK_DATEI) {
+        return Err(ungueltig("keine Datenbank enthalten".into()));
+    }
+
+    for eintrag in &manifest.eintraege {
+        let pfad = pfad_aufloesen(archiv, &eintrag.pfad)?;
+        let (groesse, sha256) = pruefsumme(pfad)
+            .await
+            .map_err(|e| ungueltig(format!("'{}' nicht lesbar ({e})", eintrag.pfad)))?;
+        if groesse != eintrag.groesse || sha256 != eintrag.sha256 {
+            return Err(ungueltig(format!(
+                "Pruefsumme von '{}' stimmt nicht",
+                eintrag.pfad
+            )));
+        }
+    }
+    Ok(manifest)
+}
+
+/// Stellt eine Sicherung in ein leeres Verzeichnis wieder her
+///
+/// Das Ziel erhaelt denselben Aufbau wie die Sicherung (ohne Manifest).
+/// Gedacht fuer den Offline-Betrieb, bevor der Server auf das Ziel zeigt.
+pub async fn wiederherstellen(archiv: &Path, ziel: &Path) -> CommanderResult<SicherungsBericht> {
+    ziel_pruefen(ziel).await?;
+    let manifest = pruefen(archiv).await?;
+
+    tokio::fs::create_dir_all(ziel).await?;
+    for eintrag in &manifest.eintraege {
+        let nach = pfad_aufloesen(ziel, &eintrag.pfad)?;
+        if let Some(eltern) = nach.parent() {
+            tokio::fs::create_dir_all(eltern).await?;
+        }
+        tokio::fs::copy(pfad_aufloesen(archiv, &eintrag.pfad)?, nach).await?;
+    }
+
+    let bericht = SicherungsBericht::aus_manifest(ziel, &manifest);
+    tracing::info!(
+        archiv = %archiv.display(),
+        ziel = %bericht.pfad,
+        schema_version = bericht.schema_version,
+        "Sicherung wiederhergestellt"
+    );
+    Ok(bericht)
+}
+
+/// Relative Pfade aller Dateien unter `wurzel` (sortiert, mit `/` getrennt)
+async fn dateien_auflisten(wurzel: &Path) -> CommanderResult<Vec<String>> {
+    let mut dateien = Vec::new();
+    if !wurzel.exists() {
+        return Ok(dateien);
+    }
+    let mut offen = vec![String::new()];
+    while let Some(relativ) = offen.pop() {
+        let mut eintraege = tokio::fs::read_dir(wurzel.join(&relativ)).await?;
+        while let Some(eintrag) = eintraege.next_entry().await? {
+            let name = eintrag.file_name().into_string().map_err(|name| {
+                CommanderError::UngueltigeEingabe(format!(
+                    "Dateiname ist kein UTF-8: {}",
+                    name.to_string_lossy()
+                ))
+            })?;
+            let pfad = if relativ.is_empty() {
+                name
+            } else {
+                format!("{relativ}/{name}")
+            };
+            let typ = eintrag.file_type().await?;
+            if typ.is_dir() {
+                offen.push(pfad);
+            } else if typ.is_file() {
+                dateien.push(pfad);
+            }
+        }
+    }
+    dateien.sort();
+    Ok(dateien)
+}
+
+/// Verbindet einen relativen Manifest-Pfad mit `basis`
+///
+/// Nur normale Pfadbestandteile sind erlaubt – ein manipuliertes Manifest
+/// kann nicht aus dem Verzeichnis heraus schreiben oder lesen.
+fn pfad_aufloesen(basis: &Path, relativ: &str) -> CommanderResult<PathBuf> {
+    let pfad = Path::new(relativ);
+    let gueltig = !relativ.is_empty()
+        && pfad
+            .components()
+            .all(|teil| matches!(teil, Component::Normal(_)));
+    if !gueltig {
+        return Err(CommanderError::UngueltigeEingabe(format!(
+            "Ungueltiger Pfad in der Sicherung: '{relativ}'"
+        )));
+    }
+    Ok(basis.join(pfad))
+}
+
+async fn verknuepfen_oder_kopieren(von: &Path, nach: &Path) -> CommanderResult<()> {
+    if let Some(eltern) = nach.parent() {
+        tokio::fs::create_dir_all(eltern).await?;
+    }
+    if tokio::fs::hard_link(von, nach).await.is_err() {
+        tokio::fs::copy(von, nach).await?;
+    }
+    Ok(())
+}
+
+/// Groesse und SHA-256 (hex) einer Datei
+async fn pruefsumme(pfad: PathBuf) -> CommanderResult<(u64, String)> {
+    tokio::task::spawn_blocking(move || {
+        let mut datei = std::fs::File::open(pfad)?;
+        let mut hasher = Sha256::new();
+        let groesse = std::io::copy(&mut datei, &mut hasher)?;
+        Ok((groesse, format!("{:x}", hasher.finalize())))
+    })
+    .await
+    .map_err(anyhow::Error::from)?
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use speakeasy_db::{
+        models::{NeueDatei, NeuerBenutzer, NeuerKanal},
+        ChannelRepository, DatabaseBackend, DatabaseConfig, FileRepository, UserRepository,
+    };
+    use std::sync::Mutex;
+
+    /// Frisches Arbeitsverzeichnis (wird am Testende entfernt)
+    fn arbeitsverzeichnis() -> PathBuf {
+        std::env::temp_dir().join(format!("speakeasy-backup-{}", uuid::Uuid::new_v4()))
+    }
+
+    async fn datei_db(pfad: &Path) -> SqliteDb {
+        SqliteDb::oeffnen(&DatabaseConfig {
+            backend: DatabaseBackend::Sqlite,
+            url: format!("sqlite://{}", pfad.display()),
+            max_verbindungen: 2,
+            sqlite_wal: true,
+        })
+        .await
+        .unwrap()
+    }
+
+    fn auftrag(
+        ziel: PathBuf,
+        meldungen: &Arc<Mutex<Vec<SicherungsFortschritt>>>,
+    ) -> SicherungsAuftrag {
+        let meldungen = Arc::clone(meldungen);
+        SicherungsAuftrag {
+            ziel,
+            dateien_einschliessen: true,
+            fortschritt: Arc::new(move |f| meldungen.lock().unwrap().push(f)),
+        }
+    }
+
+    /// Legt Benutzer, Kanal und eine hochgeladene Datei an
+    async fn quellen_anlegen(basis: &Path) -> (SicherungsQuellen, uuid::Uuid) {
+        std::fs::create_dir_all(basis).unwrap();
+        let db = datei_db(&basis.join("server.db")).await;
+        let benutzer = UserRepository::create(
+            &db,
+            NeuerBenutzer {
+                username: "gesichert",
+                password_hash: "hash",
+            },
+        )
+        .await
+        .unwrap();
+        let kanal = ChannelRepository::create(
+            &db,
+            NeuerKanal {
+                name: "Archiv",
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+
+        let inhalt = b"Protokoll der Sitzung";
+        let speicher_pfad = format!("{}/{}_protokoll.txt", kanal.id, uuid::Uuid::new_v4());
+        let dateien = basis.join("dateien");
+        let datei_pfad = dateien.join(&speicher_pfad);
+        std::fs::create_dir_all(datei_pfad.parent().unwrap()).unwrap();
+        std::fs::write(&datei_pfad, inhalt).unwrap();
+        let datei = FileRepository::create(
+            &db,
+            NeueDatei {
+                channel_id: kanal.id,
+                uploader_id: benutzer.id,
+                filename: "protokoll.txt",
+                mime_type: "text/plain",
+                size_bytes: inhalt.len() as i64,
+                storage_path: &speicher_pfad,
+                checksum: &format!("{:x}", Sha256::digest(inhalt)),
+            },
+        )
+        .await
+        .unwrap();
+
+        let quellen = SicherungsQuellen {
+            db,
+            dateien,
+            config: Some("[server]\nname = \"Gesichert\"\n".into()),
+            server_version: "1.2.3".into(),
+        };
+        (quellen, datei.id)
+    }
+
+    #[tokio::test]
+    async fn sicherung_und_wiederherstellung_im_kreis() {
+        let basis = arbeitsverzeichnis();
+        let (quellen, datei_id) = quellen_anlegen(&basis.join("quelle")).await;
+        let meldungen = Arc::new(Mutex::new(Vec::new()));
+
+        let archiv = basis.join("archiv");
+        let bericht = erstellen(&quellen, auftrag(archiv.clone(), &meldungen))
+            .await
+            .unwrap();
+        assert_eq!(bericht.dateien, 1);
+        assert_eq!(bericht.schema_version, SqliteDb::schema_version_binary());
+
+        let phasen: Vec<SicherungsPhase> = meldungen
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|m| m.phase.clone())
+            .collect();
+        assert_eq!(phasen.first(), Some(&SicherungsPhase::Datenbank));
+        assert!(phasen.contains(&SicherungsPhase::Dateien));
+        assert_eq!(phasen.last(), Some(&SicherungsPhase::Abgeschlossen));
+
+        let manifest = pruefen(&archiv).await.unwrap();
+        assert_eq!(manifest.server_version, "1.2.3");
+        assert!(manifest.eintraege.iter().any(|e| e.pfad == CONFIG_DATEI));
+
+        let ziel = basis.join("wiederhergestellt");
+        let wiederhergestellt = wiederherstellen(&archiv, &ziel).await.unwrap();
+        assert_eq!(wiederhergestellt.bytes, bericht.bytes);
+        assert_eq!(
+            std::fs::read_to_string(ziel.join(CONFIG_DATEI)).unwrap(),
+            quellen.config.clone().unwrap()
+        );
+
+        let db = datei_db(&ziel.join(DATENBANK_DATEI)).await;
+        assert!(db.get_by_name("gesichert").await.unwrap().is_some());
+        assert!(ChannelRepository::list(&db)
+            .await
+            .unwrap()
+            .iter()
+            .any(|k| k.name == "Archiv"));
+        let datei = FileRepository::get_by_id(&db, datei_id)
+            .await
+            .unwrap()
+            .unwrap();
+        let inhalt =
+            std::fs::read(ziel.join(DATEIEN_VERZEICHNIS).join(&datei.storage_path)).unwrap();
+        assert_eq!(format!("{:x}", Sha256::digest(&inhalt)), datei.checksum);
+        db.pool().close().await;
+        quellen.db.pool().close().await;
+
+        let _ = std::fs::remove_dir_all(basis);
+    }
+
+    #[tokio::test]
+    async fn nicht_leere_ziele_werden_abgelehnt() {
+        let basis = arbeitsverzeichnis();
+        let (quellen, _) = quellen_anlegen(&basis.join("quelle")).await;
+        let meldungen = Arc::new(Mutex::new(Vec::new()));
+        let archiv = basis.join("archiv");
+        erstellen(&quellen, auftrag(archiv.clone(), &meldungen))
+            .await
+            .unwrap();
+
+        // Sicherung in ein belegtes Verzeichnis meldet den Fehler auch als Fortschritt
+        meldungen.lock().unwrap().clear();
+        let fehler = erstellen(&quellen, auftrag(archiv.clone(), &meldungen))
+            .await
+            .unwrap_err();
+        assert!(matches!(fehler, CommanderError::UngueltigeEingabe(_)));
+        assert!(matches!(
+            meldungen.lock().unwrap().last().map(|m| &m.phase),
+            Some(SicherungsPhase::Fehlgeschlagen(_))
+        ));
+
+        let ziel = basis.join("belegt");
+        std::fs::create_dir_all(&ziel).unwrap();
+        std::fs::write(ziel.join("alt.db"), b"alt").unwrap();
+        let fehler = wiederherstellen(&archiv, &ziel).await.unwrap_err();
+        assert!(fehler.to_string().contains("nicht leer"), "{fehler}");
+        assert_eq!(std::fs::read(ziel.join("alt.db")).unwrap(), b"alt");
+        quellen.db.pool().close().await;
+
+        let _ = std::fs::remove_dir_all(basis);
+    }
+
+    #[tokio::test]
+    async fn veraenderte_oder_neuere_sicherungen_werden_abgelehnt() {
+        let basis = arbeitsverzeichnis();
+        let (quellen, _) = quellen_anlegen(&basis.join("quelle")).await;
+        let meldungen = Arc::new(Mutex::new(Vec::new()));
+        let archiv = basis.join("archiv");
+        erstellen(&quellen, auftrag(archiv.clone(), &meldungen))
+            .await
+            .unwrap();
+        quellen.db.pool().close().await;
+        let manifest = pruefen(&archiv).await.unwrap();
+
+        // Veraenderte Datei
+        let datei = manifest
+            .eintraege
+            .iter()
+            .find(|e| e.pfad.starts_with(DATEIEN_VERZEICHNIS))
+            .unwrap();
+        let pfad = archiv.join(&datei.pfad);
+        std::fs::remove_file(&pfad).unwrap();
+        std::fs::write(&pfad, b"Protokoll der Sitzunq").unwrap();
+        let fehler = wiederherstellen(&archiv, &basis.join("ziel"))
+            .await
+            .unwrap_err();
+        assert!(fehler.to_string().contains("Pruefsumme"), "{fehler}");
+        assert!(!basis.join("ziel").exists());
+
+        // Schema eines neueren Servers
+        let mut neuer = manifest.clone();
+        neuer.schema_version = SqliteDb::schema_version_binary() + 1;
+        std::fs::write(
+            archiv.join(MANIFEST_DATEI),
+            serde_json::to_vec(&neuer).unwrap(),
+        )
+        .unwrap();
+        let fehler = pruefen(&archiv).await.unwrap_err();
+        assert!(fehler.to_string().contains("Schema-Version"), "{fehler}");
+
+        // Pfade ausserhalb der Sicherung
+        let mut boese = manifest;
+        boese.eintraege.last_mut().unwrap().pfad = "../server.db".into();
+        std::fs::write(
+            archiv.join(MANIFEST_DATEI),
+            serde_json::to_vec(&boese).unwrap(),
+        )
+        .unwrap();
+        let fehler = pruefen(&archiv).await.unwrap_err();
+        assert!(fehler.to_string().contains("Ungueltiger Pfad"), "{fehler}");
+
+        let _ = std::fs::remove_dir_all(basis);
+    }
+}
@@ -3,29 +3,98 @@
 //! Uebersetzt geparste TCP-Befehle in Command-Enum-Werte
 //! und delegiert die Ausfuehrung an den CommandExecutor.
 
+use std::time::Duration;
+
+use speakeasy_db::models::GeplanteAktion;
 use uuid::Uuid;
 
-use crate::commands::types::{BerechtigungsWertInput, Command};
+use crate::commands::types::{BerechtigungsWertInput, Command, SeitenAnfrage};
 use crate::error::{CommanderError, CommanderResult};
 use crate::tcp::parser::ParsedCommand;
 
+/// Befehle, die [`tcp_befehl_zu_command`] kennt (Ausgabe von `help`)
+pub const BEFEHLE: &[&str] = &[
+    "serverinfo",
+    "serverhistory",
+    "serveredit",
+    "serverstop",
+    "alertcheck",
+    "serverbackup",
+    "channellist",
+    "channelcreate",
+    "channeledit",
+    "channeldelete",
+    "channelcreatefromtemplate",
+    "channelemergencymute",
+    "templatelist",
+    "templatecreate",
+    "templatedelete",
+    "clientlist",
+    "channelspeakers",
+    "clientvoice",
+    "clientkick",
+    "clientban",
+    "clientmove",
+    "clientmoveall",
+    "clientpoke",
+    "clientcleanup",
+    "accountexport",
+    "accountdelete",
+    "permlist",
+    "permset",
+    "permdel",
+    "permeffective",
+    "ftlist",
+    "ftdeletefile",
+    "ftaccesslog",
+    "messageinfo",
+    "soundlist",
+    "soundadd",
+    "sounddelete",
+    "logview",
+    "schedulelist",
+    "schedulecreate",
+    "scheduledelete",
+];
+
 /// Konvertiert einen ParsedCommand in einen Command-Enum-Wert
 pub fn tcp_befehl_zu_command(cmd: &ParsedCommand) -> CommanderResult<Command> {
     match cmd.name.as_str() {
         // --- Server ---
         "serverinfo" => Ok(Command::ServerInfo),
+        "serverhistory" => Ok(Command::ServerHistorie {
+            limit: cmd
+                .param("lines")
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(20)
+                .min(200),
+        }),
         "serveredit" => Ok(Command::ServerEdit {
             name: cmd.param("name").map(String::from),
             willkommensnachricht: cmd.param("welcomemsg").map(String::from),
             max_clients: cmd.param("maxclients").and_then(|s| s.parse().ok()),
             host_nachricht: cmd.param("hostmsg").map(String::from),
+            afk_timeout_sek: cmd.param("afktimeout").and_then(|s| s.parse().ok()),
+            afk_kanal_id: cmd
+                .param("afkchannel")
+                .and_then(|s| Uuid::parse_str(s).ok()),
+            chat_bearbeitungsfrist_sek: cmd.param("editwindow").and_then(|s| s.parse().ok()),
         }),
         "serverstop" => Ok(Command::ServerStop {
             grund: cmd.param("reason").map(String::from),
         }),
+        "alertcheck" => Ok(Command::AlertRegelnTesten),
+        "serverbackup" => Ok(Command::BackupErstellen {
+            ziel_pfad: cmd.required_param("path")?.to_string(),
+            include_files: cmd.param("files").map(|s| s == "1").unwrap_or(true),
+        }),
 
         // --- Kanaele ---
-        "channellist" => Ok(Command::KanalListe),
+        // channellist [name=<teil>] [page=] [page_size=]
+        "channellist" => Ok(Command::KanalListe {
+            name: cmd.param("name").map(String::from),
+            seite: seiten_param(cmd)?,
+        }),
         "channelcreate" => {
             let name = cmd.required_param("name")?.to_string();
             Ok(Command::KanalErstellen {
@@ -52,14 +121,58 @@ pub fn tcp_befehl_zu_command(cmd: &ParsedCommand) -> CommanderResult<Command> {
                 thema: cmd.param("topic").map(|s| Some(s.to_string())),
                 max_clients: cmd.param("maxclients").and_then(|s| s.parse().ok()),
                 sort_order: cmd.param("order").and_then(|s| s.parse().ok()),
+                langsammodus_sek: cmd.param("slowmode").and_then(|s| s.parse().ok()),
+                bearbeitungsfrist_sek: bearbeitungsfrist_param(cmd)?,
             })
         }
         "channeldelete" => Ok(Command::KanalLoeschen {
             id: cmd.uuid_param("cid")?,
         }),
+        "channelcreatefromtemplate" => Ok(Command::KanalbaumAusVorlage {
+            vorlage_id: cmd.uuid_param("tid")?,
+            parent_id: cmd.optional_uuid_param("cpid")?,
+            name_prefix: cmd.param("prefix").map(String::from),
+        }),
+        // channelemergencymute cid=<kanal> active=1|0
+        "channelemergencymute" => Ok(Command::KanalNotfallStumm {
+            kanal_id: cmd.uuid_param("cid")?,
+            aktivieren: cmd.required_param("active")? == "1",
+        }),
+
+        // --- Kanal-Vorlagen ---
+        "templatelist" => Ok(Command::VorlageListe),
+        "templatecreate" => {
+            // JSON-Definition: Anfuehrungszeichen als \" und Leerzeichen als \s escapen
+            let definition = cmd.required_param("definition")?;
+            Ok(Command::VorlageErstellen {
+                name: cmd.required_param("name")?.to_string(),
+                beschreibung: cmd.param("desc").map(String::from),
+                definition: serde_json::from_str(definition).map_err(|e| {
+                    CommanderError::UngueltigeEingabe(format!(
+                        "Ungueltiges JSON fuer 'definition': {e}"
+                    ))
+                })?,
+            })
+        }
+        "templatedelete" => Ok(Command::VorlageLoeschen {
+            id: cmd.uuid_param("tid")?,
+        }),
 
         // --- Clients ---
-        "clientlist" => Ok(Command::ClientListe),
+        // clientlist [cid=] [username=<teil>] [page=] [page_size=]
+        "clientlist" => Ok(Command::ClientListe {
+            kanal_id: cmd.optional_uuid_param("cid")?,
+            username: cmd.param("username").map(String::from),
+            seite: seiten_param(cmd)?,
+        }),
+        // channelspeakers cid=<kanal>
+        "channelspeakers" => Ok(Command::AktiveSprecher {
+            kanal_id: cmd.uuid_param("cid")?,
+        }),
+        // clientvoice clid=<client>
+        "clientvoice" => Ok(Command::VoiceDiagnose {
+            client_id: cmd.uuid_param("clid")?,
+        }),
         "clientkick" => Ok(Command::ClientKicken {
             client_id: cmd.uuid_param("clid")?,
             grund: cmd.param("reason").map(String::from),
@@ -69,16 +182,43 @@ pub fn tcp_befehl_zu_command(cmd: &ParsedCommand) -> CommanderResult<Command> {
             dauer_secs: cmd.param("duration").and_then(|s| s.parse().ok()),
             grund: cmd.param("reason").map(String::from),
             ip_bannen: cmd.param("banip").map(|s| s == "1").unwrap_or(false),
+            inhalte_entfernen: sekunden_param(cmd, "removecontent")?,
+        }),
+        // clientcleanup uid=<benutzer> period=<sek> [cid=<kanal>]
+        // clientcleanup uid=<benutzer> jobid=<uuid> (Fortsetzen)
+        "clientcleanup" => Ok(Command::BenutzerInhalteBereinigen {
+            user_id: cmd.uuid_param("uid")?,
+            zeitraum: sekunden_param(cmd, "period")?.unwrap_or_default(),
+            nur_kanal: cmd.optional_uuid_param("cid")?,
+            job_id: cmd.optional_uuid_param("jobid")?,
         }),
         "clientmove" => Ok(Command::ClientVerschieben {
             client_id: cmd.uuid_param("clid")?,
             kanal_id: cmd.uuid_param("cid")?,
         }),
+        // clientmoveall scid=<quelle> cid=<ziel> [clid=<uuid>,<uuid>...] [partial=1]
+        "clientmoveall" => Ok(Command::ClientsVerschiebenAlle {
+            von_kanal_id: cmd.uuid_param("scid")?,
+            nach_kanal_id: cmd.uuid_param("cid")?,
+            nur_user_ids: cmd
+                .param("clid")
+                .map(|_| uuid_liste_param(cmd, "clid"))
+                .transpose()?,
+            teilweise: cmd.param("partial").map(|s| s == "1").unwrap_or(false),
+        }),
         "clientpoke" => Ok(Command::ClientPoken {
             client_id: cmd.uuid_param("clid")?,
             nachricht: cmd.required_param("msg")?.to_string(),
         }),
 
+        // --- Konten ---
+        "accountexport" => Ok(Command::KontoExport {
+            benutzer_id: cmd.uuid_param("uid")?,
+        }),
+        "accountdelete" | "clientdbdelete" => Ok(Command::KontoLoeschen {
+            benutzer_id: cmd.uuid_param("uid")?,
+        }),
+
         // --- Berechtigungen ---
         "permlist" => Ok(Command::BerechtigungListe {
             ziel: cmd.required_param("target")?.to_string(),
@@ -95,6 +235,10 @@ pub fn tcp_befehl_zu_command(cmd: &ParsedCommand) -> CommanderResult<Command> {
             permission: cmd.required_param("permsid")?.to_string(),
             scope: cmd.param("scope").unwrap_or("server").to_string(),
         }),
+        "permeffective" => Ok(Command::BerechtigungEffektiv {
+            user_id: cmd.uuid_param("uid")?,
+            kanal_id: cmd.optional_uuid_param("cid")?,
+        }),
 
         // --- Dateien ---
         "ftlist" | "filelist" => Ok(Command::DateiListe {
@@ -103,6 +247,40 @@ pub fn tcp_befehl_zu_command(cmd: &ParsedCommand) -> CommanderResult<Command> {
         "ftdeletefile" | "filedelete" => Ok(Command::DateiLoeschen {
             datei_id: cmd.required_param("fid")?.to_string(),
         }),
+        "ftaccesslog" | "fileaccesslog" => Ok(Command::DateiZugriffe {
+            datei_id: cmd.optional_uuid_param("fid")?,
+            benutzer_id: cmd.optional_uuid_param("uid")?,
+            von: zeitstempel_param(cmd, "from")?,
+            bis: zeitstempel_param(cmd, "to")?,
+            limit: cmd
+                .param("lines")
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(50),
+            offset: cmd
+                .param("begin_pos")
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(0),
+        }),
+
+        // --- Chat ---
+        // messageinfo mid=<nachricht>
+        "messageinfo" => Ok(Command::NachrichtInfo {
+            nachricht_id: cmd.uuid_param("mid")?,
+        }),
+
+        // --- Soundboard ---
+        "soundlist" => Ok(Command::SoundboardListe {
+            kanal_id: cmd.optional_uuid_param("cid")?,
+        }),
+        // soundadd name=Gong fid=<datei> [cid=<kanal>]
+        "soundadd" => Ok(Command::SoundboardRegistrieren {
+            name: cmd.required_param("name")?.to_string(),
+            datei_id: cmd.uuid_param("fid")?,
+            kanal_id: cmd.optional_uuid_param("cid")?,
+        }),
+        "sounddelete" => Ok(Command::SoundboardEntfernen {
+            id: cmd.uuid_param("sid")?,
+        }),
 
         // --- Logs ---
         "logview" => Ok(Command::LogAbfragen {
@@ -117,12 +295,126 @@ pub fn tcp_befehl_zu_command(cmd: &ParsedCommand) -> CommanderResult<Command> {
             aktion_filter: cmd.param("filter").map(String::from),
         }),
 
+        // --- Zeitplaner ---
+        "schedulelist" => Ok(Command::ZeitplanListe),
+        // Cron-Ausdruck mit escapten Leerzeichen: cron=0\s20\s*\s*\s5 tz=Europe/Berlin
+        "schedulecreate" => Ok(Command::ZeitplanErstellen {
+            name: cmd.required_param("name")?.to_string(),
+            aktion: geplante_aktion_param(cmd)?,
+            einmalig: zeitstempel_param(cmd, "at")?,
+            cron: cmd.param("cron").map(String::from),
+            zeitzone: cmd.param("tz").map(String::from),
+        }),
+        "scheduledelete" => Ok(Command::ZeitplanAbbrechen {
+            id: cmd.uuid_param("sid")?,
+        }),
+
         other => Err(CommanderError::Protokoll(format!(
             "Unbekannter Befehl: {other}"
         ))),
     }
 }
 
+/// Liest einen optionalen Unix-Zeitstempel (Sekunden) aus den Parametern
+fn zeitstempel_param(
+    cmd: &ParsedCommand,
+    name: &str,
+) -> CommanderResult<Option<chrono::DateTime<chrono::Utc>>> {
+    cmd.param(name)
+        .map(|s| {
+            s.parse::<i64>()
+                .ok()
+                .and_then(|secs| chrono::DateTime::from_timestamp(secs, 0))
+                .ok_or_else(|| {
+                    CommanderError::UngueltigeEingabe(format!(
+                        "Ungueltiger Zeitstempel fuer '{name}': {s}"
+                    ))
+                })
+        })
+        .transpose()
+}
+
+/// Liest `page=` und `page_size=` (fehlend = Seite 1 mit Standardgroesse)
+fn seiten_param(cmd: &ParsedCommand) -> CommanderResult<SeitenAnfrage> {
+    let zahl = |name: &str| {
+        cmd.param(name)
+            .map(|s| {
+                s.parse::<u32>().map_err(|_| {
+                    CommanderError::UngueltigeEingabe(format!("Ungueltige Zahl fuer '{name}': {s}"))
+                })
+            })
+            .transpose()
+    };
+    Ok(SeitenAnfrage::neu(zahl("page")?, zahl("page_size")?))
+}
+
+/// Liest eine optionale Dauer in Sekunden aus den Parametern
+fn sekunden_param(cmd: &ParsedCommand, name: &str) -> CommanderResult<Option<Duration>> {
+    cmd.param(name)
+        .map(|s| {
+            s.parse::<u64>().map(Duration::from_secs).map_err(|_| {
+                CommanderError::UngueltigeEingabe(format!("Ungueltige Dauer fuer '{name}': {s}"))
+            })
+        })
+        .transpose()
+}
+
+/// Liest `editwindow=<sek>|default` (`default` = Server-Standard)
+fn bearbeitungsfrist_param(cmd: &ParsedCommand) -> CommanderResult<Option<Option<u32>>> {
+    cmd.param("editwindow")
+        .map(|s| match s {
+            "default" => Ok(None),
+            _ => s.parse().map(Some).map_err(|_| {
+                CommanderError::UngueltigeEingabe(format!(
+                    "Ungueltige Frist fuer 'editwindow': {s}"
+                ))
+            }),
+        })
+        .transpose()
+}
+
+/// Liest die geplante Aktion aus `action` und ihren Parametern
+///
+/// - `action=channelcreatefromtemplate tid= [cpid=] [prefix=] [duration=<min>]`
+/// - `action=channeldelete cid=<uuid>[,<uuid>...]`
+fn geplante_aktion_param(cmd: &ParsedCommand) -> CommanderResult<GeplanteAktion> {
+    match cmd.required_param("action")? {
+        "channelcreatefromtemplate" => Ok(GeplanteAktion::KanalAusVorlage {
+            vorlage_id: cmd.uuid_param("tid")?,
+            parent_id: cmd.optional_uuid_param("cpid")?,
+            name_prefix: cmd.param("prefix").map(String::from),
+            loeschen_nach_min: cmd
+                .param("duration")
+                .map(|s| {
+                    s.parse().map_err(|_| {
+                        CommanderError::UngueltigeEingabe(format!(
+                            "Ungueltige Dauer fuer 'duration': {s}"
+                        ))
+                    })
+                })
+                .transpose()?,
+        }),
+        "channeldelete" => Ok(GeplanteAktion::KanaeleLoeschen {
+            kanal_ids: uuid_liste_param(cmd, "cid")?,
+        }),
+        andere => Err(CommanderError::UngueltigeEingabe(format!(
+            "Unbekannte Aktion: {andere}"
+        ))),
+    }
+}
+
+/// Liest eine kommagetrennte UUID-Liste (`cid=<uuid>,<uuid>`)
+fn uuid_liste_param(cmd: &ParsedCommand, name: &str) -> CommanderResult<Vec<Uuid>> {
+    cmd.required_param(name)?
+        .split(',')
+        .map(|s| {
+            Uuid::parse_str(s).map_err(|_| {
+                CommanderError::UngueltigeEingabe(format!("Ungueltige UUID fuer '{name}': {s}"))
+            })
+        })
+        .collect()
+}
+
 fn parse_perm_value(cmd: &ParsedCommand) -> CommanderResult<BerechtigungsWertInput> {
     if let Some(v) = cmd.param("permvalue") {
         match v {
@@ -152,11 +444,48 @@ mod tests {
         assert_eq!(cmd, Command::ServerInfo);
     }
 
+    #[test]
+    fn serverhistory_befehl() {
+        let parsed = parse_line("serverhistory").unwrap();
+        let cmd = tcp_befehl_zu_command(&parsed).unwrap();
+        assert_eq!(cmd, Command::ServerHistorie { limit: 20 });
+
+        let parsed = parse_line("serverhistory lines=5000").unwrap();
+        let cmd = tcp_befehl_zu_command(&parsed).unwrap();
+        assert_eq!(cmd, Command::ServerHistorie { limit: 200 });
+    }
+
+    #[test]
+    fn alertcheck_befehl() {
+        let parsed = parse_line("alertcheck").unwrap();
+        let cmd = tcp_befehl_zu_command(&parsed).unwrap();
+        assert_eq!(cmd, Command::AlertRegelnTesten);
+    }
+
     #[test]
     fn channellist_befehl() {
         let parsed = parse_line("channellist").unwrap();
         let cmd = tcp_befehl_zu_command(&parsed).unwrap();
-        assert_eq!(cmd, Command::KanalListe);
+        assert_eq!(
+            cmd,
+            Command::KanalListe {
+                name: None,
+                seite: SeitenAnfrage::default(),
+            }
+        );
+
+        let parsed = parse_line("channellist name=lobby page=3 page_size=5000").unwrap();
+        let cmd = tcp_befehl_zu_command(&parsed).unwrap();
+        assert_eq!(
+            cmd,
+            Command::KanalListe {
+                name: Some("lobby".into()),
+                seite: SeitenAnfrage::neu(Some(3), Some(5000)),
+            }
+        );
+
+        let parsed = parse_line("clientlist page=zwei").unwrap();
+        assert!(tcp_befehl_zu_command(&parsed).is_err());
     }
 
     #[test]
@@ -169,6 +498,46 @@ mod tests {
         }
     }
 
+    #[test]
+    fn channeledit_mit_langsam_modus() {
+        let id = Uuid::new_v4();
+        let parsed = parse_line(&format!("channeledit cid={id} slowmode=15")).unwrap();
+        let cmd = tcp_befehl_zu_command(&parsed).unwrap();
+        assert!(matches!(
+            cmd,
+            Command::KanalBearbeiten {
+                langsammodus_sek: Some(15),
+                name: None,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn channeledit_mit_bearbeitungsfrist() {
+        let id = Uuid::new_v4();
+        let frist = |wert: &str| {
+            let parsed = parse_line(&format!("channeledit cid={id} editwindow={wert}")).unwrap();
+            match tcp_befehl_zu_command(&parsed) {
+                Ok(Command::KanalBearbeiten {
+                    bearbeitungsfrist_sek,
+                    ..
+                }) => Ok(bearbeitungsfrist_sek),
+                Ok(andere) => panic!("Falscher Command-Typ: {andere:?}"),
+                Err(e) => Err(e),
+            }
+        };
+        assert_eq!(frist("900").unwrap(), Some(Some(900)));
+        assert_eq!(frist("default").unwrap(), Some(None));
+        assert!(frist("bald").is_err());
+
+        let parsed = parse_line(&format!("messageinfo mid={id}")).unwrap();
+        assert_eq!(
+            tcp_befehl_zu_command(&parsed).unwrap(),
+            Command::NachrichtInfo { nachricht_id: id }
+        );
+    }
+
     #[test]
     fn channelcreate_ohne_name_gibt_fehler() {
         let parsed = parse_line("channelcreate topic=Test").unwrap();
@@ -189,12 +558,311 @@ mod tests {
         }
     }
 
+    #[test]
+    fn clientban_und_clientcleanup_mit_zeitraum() {
+        let id = Uuid::new_v4();
+        let kanal = Uuid::new_v4();
+        let parsed = parse_line(&format!("clientban clid={id} removecontent=3600")).unwrap();
+        assert!(matches!(
+            tcp_befehl_zu_command(&parsed).unwrap(),
+            Command::ClientBannen { inhalte_entfernen: Some(d), .. } if d == Duration::from_secs(3600)
+        ));
+
+        let parsed = parse_line(&format!("clientcleanup uid={id} period=600 cid={kanal}")).unwrap();
+        assert_eq!(
+            tcp_befehl_zu_command(&parsed).unwrap(),
+            Command::BenutzerInhalteBereinigen {
+                user_id: id,
+                zeitraum: Duration::from_secs(600),
+                nur_kanal: Some(kanal),
+                job_id: None,
+            }
+        );
+
+        let parsed = parse_line(&format!("clientcleanup uid={id} period=gestern")).unwrap();
+        assert!(tcp_befehl_zu_command(&parsed).is_err());
+    }
+
+    #[test]
+    fn clientmoveall_befehl() {
+        let von = Uuid::new_v4();
+        let nach = Uuid::new_v4();
+        let (a, b) = (Uuid::new_v4(), Uuid::new_v4());
+        let parsed = parse_line(&format!(
+            "clientmoveall scid={von} cid={nach} clid={a},{b} partial=1"
+        ))
+        .unwrap();
+        assert_eq!(
+            tcp_befehl_zu_command(&parsed).unwrap(),
+            Command::ClientsVerschiebenAlle {
+                von_kanal_id: von,
+                nach_kanal_id: nach,
+                nur_user_ids: Some(vec![a, b]),
+                teilweise: true,
+            }
+        );
+
+        let parsed = parse_line(&format!("clientmoveall scid={von} cid={nach}")).unwrap();
+        assert_eq!(
+            tcp_befehl_zu_command(&parsed).unwrap(),
+            Command::ClientsVerschiebenAlle {
+                von_kanal_id: von,
+                nach_kanal_id: nach,
+                nur_user_ids: None,
+                teilweise: false,
+            }
+        );
+
+        let parsed = parse_line(&format!("clientmoveall scid={von} cid={nach} clid=x")).unwrap();
+        assert!(tcp_befehl_zu_command(&parsed).is_err());
+    }
+
+    #[test]
+    fn channelspeakers_befehl() {
+        let kanal = Uuid::new_v4();
+        let parsed = parse_line(&format!("channelspeakers cid={kanal}")).unwrap();
+        assert_eq!(
+            tcp_befehl_zu_command(&parsed).unwrap(),
+            Command::AktiveSprecher { kanal_id: kanal }
+        );
+        assert!(tcp_befehl_zu_command(&parse_line("channelspeakers").unwrap()).is_err());
+    }
+
+    #[test]
+    fn clientvoice_befehl() {
+        let client = Uuid::new_v4();
+        let parsed = parse_line(&format!("clientvoice clid={client}")).unwrap();
+        assert_eq!(
+            tcp_befehl_zu_command(&parsed).unwrap(),
+            Command::VoiceDiagnose { client_id: client }
+        );
+    }
+
+    #[test]
+    fn soundboard_befehle() {
+        let datei = Uuid::new_v4();
+        let kanal = Uuid::new_v4();
+        let parsed = parse_line(&format!("soundadd name=Gong fid={datei} cid={kanal}")).unwrap();
+        assert_eq!(
+            tcp_befehl_zu_command(&parsed).unwrap(),
+            Command::SoundboardRegistrieren {
+                name: "Gong".into(),
+                datei_id: datei,
+                kanal_id: Some(kanal),
+            }
+        );
+        let parsed = parse_line("soundlist").unwrap();
+        assert_eq!(
+            tcp_befehl_zu_command(&parsed).unwrap(),
+            Command::SoundboardListe { kanal_id: None }
+        );
+        let parsed = parse_line("soundadd name=Gong").unwrap();
+        assert!(tcp_befehl_zu_command(&parsed).is_err());
+    }
+
+    #[test]
+    fn channelemergencymute_befehl() {
+        let kanal = Uuid::new_v4();
+        let parsed = parse_line(&format!("channelemergencymute cid={kanal} active=1")).unwrap();
+        assert_eq!(
+            tcp_befehl_zu_command(&parsed).unwrap(),
+            Command::KanalNotfallStumm {
+                kanal_id: kanal,
+                aktivieren: true,
+            }
+        );
+        let parsed = parse_line(&format!("channelemergencymute cid={kanal} active=0")).unwrap();
+        assert!(matches!(
+            tcp_befehl_zu_command(&parsed).unwrap(),
+            Command::KanalNotfallStumm {
+                aktivieren: false,
+                ..
+            }
+        ));
+        let parsed = parse_line(&format!("channelemergencymute cid={kanal}")).unwrap();
+        assert!(tcp_befehl_zu_command(&parsed).is_err());
+    }
+
+    #[test]
+    fn konto_befehle() {
+        let benutzer = Uuid::new_v4();
+        let parsed = parse_line(&format!("accountexport uid={benutzer}")).unwrap();
+        assert_eq!(
+            tcp_befehl_zu_command(&parsed).unwrap(),
+            Command::KontoExport {
+                benutzer_id: benutzer
+            }
+        );
+        let parsed = parse_line(&format!("clientdbdelete uid={benutzer}")).unwrap();
+        assert_eq!(
+            tcp_befehl_zu_command(&parsed).unwrap(),
+            Command::KontoLoeschen {
+                benutzer_id: benutzer
+            }
+        );
+        assert!(tcp_befehl_zu_command(&parse_line("accountdelete").unwrap()).is_err());
+    }
+
+    #[test]
+    fn serverbackup_befehl() {
+        let parsed = parse_line("serverbackup path=/var/backups/sb1 files=0").unwrap();
+        assert_eq!(
+            tcp_befehl_zu_command(&parsed).unwrap(),
+            Command::BackupErstellen {
+                ziel_pfad: "/var/backups/sb1".into(),
+                include_files: false,
+            }
+        );
+        let parsed = parse_line("serverbackup path=/var/backups/sb2").unwrap();
+        assert!(matches!(
+            tcp_befehl_zu_command(&parsed).unwrap(),
+            Command::BackupErstellen {
+                include_files: true,
+                ..
+            }
+        ));
+        assert!(tcp_befehl_zu_command(&parse_line("serverbackup").unwrap()).is_err());
+    }
+
+    #[test]
+    fn serveredit_mit_afk_richtlinie() {
+        let kanal = Uuid::new_v4();
+        let zeile = format!("serveredit afktimeout=900 afkchannel={kanal}");
+        let parsed = parse_line(&zeile).unwrap();
+        let cmd = tcp_befehl_zu_command(&parsed).unwrap();
+        if let Command::ServerEdit {
+            afk_timeout_sek,
+            afk_kanal_id,
+            ..
+        } = cmd
+        {
+            assert_eq!(afk_timeout_sek, Some(900));
+            assert_eq!(afk_kanal_id, Some(kanal));
+        } else {
+            panic!("Falscher Command-Typ");
+        }
+    }
+
+    #[test]
+    fn permeffective_mit_kanal() {
+        let uid = Uuid::new_v4();
+        let cid = Uuid::new_v4();
+        let parsed = parse_line(&format!("permeffective uid={uid} cid={cid}")).unwrap();
+        let cmd = tcp_befehl_zu_command(&parsed).unwrap();
+        assert_eq!(
+            cmd,
+            Command::BerechtigungEffektiv {
+                user_id: uid,
+                kanal_id: Some(cid),
+            }
+        );
+    }
+
+    #[test]
+    fn help_listet_nur_bekannte_befehle() {
+        for name in BEFEHLE {
+            let parsed = parse_line(name).unwrap();
+            if let Err(e) = tcp_befehl_zu_command(&parsed) {
+                assert!(!matches!(e, CommanderError::Protokoll(_)), "{name}: {e}");
+            }
+        }
+    }
+
     #[test]
     fn unbekannter_befehl_gibt_fehler() {
         let parsed = parse_line("unbekannt").unwrap();
         assert!(tcp_befehl_zu_command(&parsed).is_err());
     }
 
+    #[test]
+    fn ftaccesslog_mit_zeitraum() {
+        let fid = Uuid::new_v4();
+        let parsed =
+            parse_line(&format!("ftaccesslog fid={fid} from=1700000000 lines=10")).unwrap();
+        let cmd = tcp_befehl_zu_command(&parsed).unwrap();
+        if let Command::DateiZugriffe {
+            datei_id,
+            benutzer_id,
+            von,
+            bis,
+            limit,
+            ..
+        } = cmd
+        {
+            assert_eq!(datei_id, Some(fid));
+            assert!(benutzer_id.is_none());
+            assert_eq!(von.map(|v| v.timestamp()), Some(1_700_000_000));
+            assert!(bis.is_none());
+            assert_eq!(limit, 10);
+        } else {
+            panic!("Falscher Command-Typ");
+        }
+    }
+
+    #[test]
+    fn channelcreatefromtemplate_befehl() {
+        let tid = Uuid::new_v4();
+        let parsed = parse_line(&format!(
+            "channelcreatefromtemplate tid={tid} prefix=CS:\\s"
+        ))
+        .unwrap();
+        let cmd = tcp_befehl_zu_command(&parsed).unwrap();
+        assert_eq!(
+            cmd,
+            Command::KanalbaumAusVorlage {
+                vorlage_id: tid,
+                parent_id: None,
+                name_prefix: Some("CS: ".into()),
+            }
+        );
+    }
+
+    #[test]
+    fn templatecreate_mit_json() {
+        let parsed = parse_line(
+            r#"templatecreate name=Turnier definition={\"name\":\"Turnier\",\"children\":[{\"name\":\"Lobby\"}]}"#,
+        )
+        .unwrap();
+        let cmd = tcp_befehl_zu_command(&parsed).unwrap();
+        if let Command::VorlageErstellen { definition, .. } = cmd {
+            assert_eq!(definition["children"][0]["name"], "Lobby");
+        } else {
+            panic!("Falscher Command-Typ");
+        }
+
+        let parsed = parse_line("templatecreate name=X definition=kein-json").unwrap();
+        assert!(tcp_befehl_zu_command(&parsed).is_err());
+    }
+
+    #[test]
+    fn schedulecreate_wiederkehrend() {
+        let tid = Uuid::new_v4();
+        let parsed = parse_line(&format!(
+            "schedulecreate name=Event action=channelcreatefromtemplate tid={tid} duration=180 \
+             cron=0\\s20\\s*\\s*\\s5 tz=Europe/Berlin"
+        ))
+        .unwrap();
+        let cmd = tcp_befehl_zu_command(&parsed).unwrap();
+        assert_eq!(
+            cmd,
+            Command::ZeitplanErstellen {
+                name: "Event".into(),
+                aktion: GeplanteAktion::KanalAusVorlage {
+                    vorlage_id: tid,
+                    parent_id: None,
+                    name_prefix: None,
+                    loeschen_nach_min: Some(180),
+                },
+                einmalig: None,
+                cron: Some("0 20 * * 5".into()),
+                zeitzone: Some("Europe/Berlin".into()),
+            }
+        );
+
+        let parsed = parse_line("schedulecreate name=X action=reboot at=1700000000").unwrap();
+        assert!(tcp_befehl_zu_command(&parsed).is_err());
+    }
+
     #[test]
     fn logview_mit_limit() {
         let parsed = parse_line("logview lines=100 begin_pos=50").unwrap();
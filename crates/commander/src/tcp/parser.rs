@@ -3,6 +3,10 @@
 //! Parst zeilenbasierte Befehle im Format:
 //!   befehlsname key1=value1 key2="value with spaces" key3=wert3
 //!
+//! Antworten bestehen aus optionalen Datenzeilen (`key=value`, mehrere
+//! Eintraege durch `|` getrennt) und enden mit `error id=N msg=...`;
+//! `error id=0 msg=ok` meldet Erfolg.
+//!
 //! Sonderzeichen in Werten werden mit Backslash escaped:
 //!   \s = Leerzeichen, \n = Newline, \\ = Backslash, \| = Pipe
 
@@ -40,6 +44,17 @@ impl ParsedCommand {
         })
     }
 
+    /// Gibt einen optionalen Parameter als UUID zurueck
+    pub fn optional_uuid_param(&self, key: &str) -> CommanderResult<Option<uuid::Uuid>> {
+        self.param(key)
+            .map(|s| {
+                uuid::Uuid::parse_str(s).map_err(|_| {
+                    CommanderError::UngueltigeEingabe(format!("Ungueltige UUID fuer '{key}': {s}"))
+                })
+            })
+            .transpose()
+    }
+
     /// Gibt einen Parameter als u64 zurueck
     pub fn u64_param(&self, key: &str) -> CommanderResult<u64> {
         let s = self.required_param(key)?;
@@ -158,16 +173,33 @@ pub fn encode_value(s: &str) -> String {
         .replace('|', "\\|")
 }
 
-/// Erstellt eine Erfolgs-Antwortzeile
+/// Abschlusszeile jeder erfolgreichen Antwort
+pub const OK_ZEILE: &str = "error id=0 msg=ok\n";
+
+/// Formatiert einen Eintrag als `key=value`-Paare
+pub fn eintrag(params: &[(&str, &str)]) -> String {
+    params
+        .iter()
+        .map(|(k, v)| format!("{}={}", k, encode_value(v)))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Erstellt eine Erfolgsantwort: optionale Datenzeile, dann [`OK_ZEILE`]
 pub fn ok_antwort(params: &[(&str, &str)]) -> String {
     if params.is_empty() {
-        "ok\n".to_string()
+        OK_ZEILE.to_string()
     } else {
-        let kv: Vec<String> = params
-            .iter()
-            .map(|(k, v)| format!("{}={}", k, encode_value(v)))
-            .collect();
-        format!("ok {}\n", kv.join(" "))
+        format!("{}\n{OK_ZEILE}", eintrag(params))
+    }
+}
+
+/// Erstellt eine Listenantwort: Eintraege durch `|` getrennt, dann [`OK_ZEILE`]
+pub fn liste_antwort(eintraege: &[String]) -> String {
+    if eintraege.is_empty() {
+        OK_ZEILE.to_string()
+    } else {
+        format!("{}\n{OK_ZEILE}", eintraege.join("|"))
     }
 }
 
@@ -227,15 +259,23 @@ mod tests {
 
     #[test]
     fn ok_antwort_ohne_params() {
-        assert_eq!(ok_antwort(&[]), "ok\n");
+        assert_eq!(ok_antwort(&[]), "error id=0 msg=ok\n");
     }
 
     #[test]
     fn ok_antwort_mit_params() {
         let antwort = ok_antwort(&[("name", "Mein Server"), ("clients", "5")]);
-        assert!(antwort.starts_with("ok "));
-        assert!(antwort.contains("name=Mein\\sServer"));
-        assert!(antwort.contains("clients=5"));
+        assert_eq!(antwort, "name=Mein\\sServer clients=5\nerror id=0 msg=ok\n");
+    }
+
+    #[test]
+    fn liste_antwort_trennt_eintraege_mit_pipe() {
+        let eintraege = vec![eintrag(&[("cid", "1")]), eintrag(&[("cid", "2")])];
+        assert_eq!(
+            liste_antwort(&eintraege),
+            "cid=1|cid=2\nerror id=0 msg=ok\n"
+        );
+        assert_eq!(liste_antwort(&[]), OK_ZEILE);
     }
 
     #[test]
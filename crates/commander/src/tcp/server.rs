@@ -1,23 +1,34 @@
 //! TCP/TLS-Server fuer den Commander (ServerQuery-Stil)
 //!
-//! Line-based Protokoll ueber TLS. Kein Plaintext.
+//! Line-based Protokoll, mit [`TlsKonfig`] ueber TLS. Ohne TLS nur auf
+//! localhost oder in einem vertrauenswuerdigen Netz betreiben.
 //! Format: Befehlsname [key=value ...]\n
-//! Antworten: ok [...] oder error id=N msg=...
+//! Antworten: [Datenzeile\n]error id=N msg=... (`error id=0 msg=ok` = Erfolg)
+//!
+//! Nach dem Shutdown-Signal werden keine Verbindungen mehr angenommen;
+//! ein laufender Befehl wird noch beantwortet, dann schliesst die Verbindung.
 
 use std::net::SocketAddr;
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
 
 use anyhow::Result;
-use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader};
 use tokio::net::TcpListener;
-use tokio_rustls::TlsAcceptor;
+use tokio::sync::watch;
+use tokio::task::JoinSet;
 
 use crate::rate_limit::RateLimiter;
 use crate::rest::CommanderState;
-use crate::tcp::commands::tcp_befehl_zu_command;
-use crate::tcp::parser::{fehler_antwort_tcp, ok_antwort, parse_line};
-use crate::tcp::session::TcpSession;
+use crate::tcp::commands::{tcp_befehl_zu_command, BEFEHLE};
+use crate::tcp::parser::{
+    eintrag, fehler_antwort_tcp, liste_antwort, ok_antwort, parse_line, OK_ZEILE,
+};
+use crate::tcp::session::{SessionZustand, TcpSession};
+use crate::tls::{TlsKonfig, TlsQuelle, HANDSHAKE_ZEITLIMIT};
+
+/// Befehle, die die Verbindung selbst behandelt
+const SITZUNGS_BEFEHLE: &[&str] = &["login", "quit", "help"];
 
 /// TCP/TLS-Server-Konfiguration
 #[derive(Debug, Clone)]
@@ -25,6 +36,8 @@ pub struct TcpServerKonfig {
     pub bind_addr: SocketAddr,
     pub max_verbindungen: usize,
     pub zeilenlimit_bytes: usize,
+    /// TLS fuer alle Verbindungen; `None` = unverschluesselt
+    pub tls: Option<TlsKonfig>,
 }
 
 impl Default for TcpServerKonfig {
@@ -33,6 +46,7 @@ impl Default for TcpServerKonfig {
             bind_addr: "127.0.0.1:9301".parse().unwrap(),
             max_verbindungen: 100,
             zeilenlimit_bytes: 8192,
+            tls: None,
         }
     }
 }
@@ -40,29 +54,67 @@ impl Default for TcpServerKonfig {
 /// TCP/TLS-Commander-Server
 pub struct TcpServer {
     konfig: TcpServerKonfig,
+    bereit: Option<Box<dyn FnOnce(SocketAddr) + Send>>,
 }
 
 impl TcpServer {
     pub fn neu(konfig: TcpServerKonfig) -> Self {
-        Self { konfig }
+        Self {
+            konfig,
+            bereit: None,
+        }
+    }
+
+    /// Meldet die gebundene Adresse, sobald der Listener Verbindungen annimmt
+    pub fn bei_bereitschaft(mut self, melden: impl FnOnce(SocketAddr) + Send + 'static) -> Self {
+        self.bereit = Some(Box::new(melden));
+        self
     }
 
     /// Startet den TCP/TLS-Server
+    ///
+    /// Kehrt zurueck, sobald `shutdown_rx` `true` meldet (oder der Sender
+    /// verworfen wird) und alle Verbindungen geschlossen sind.
     pub async fn starten(
-        self,
+        mut self,
         state: CommanderState,
         rate_limiter: Arc<RateLimiter>,
-        tls_acceptor: TlsAcceptor,
+        shutdown_rx: watch::Receiver<bool>,
     ) -> Result<()> {
+        // Zertifikate vor dem Binden laden: Fehler verhindern den Start
+        let quelle = match self.konfig.tls.take() {
+            Some(tls) => Some(Arc::new(TlsQuelle::laden(tls, &[])?)),
+            None => None,
+        };
+        let nachladen = quelle
+            .as_ref()
+            .map(|quelle| tokio::spawn(Arc::clone(quelle).beobachten()));
+
         let listener = TcpListener::bind(self.konfig.bind_addr).await?;
-        tracing::info!(addr = %self.konfig.bind_addr, "TCP/TLS-Commander-Server gestartet");
+        tracing::info!(
+            addr = %self.konfig.bind_addr,
+            tls = quelle.is_some(),
+            "TCP-Commander-Server gestartet"
+        );
+        if let Some(melden) = self.bereit.take() {
+            melden(listener.local_addr()?);
+        }
 
         // Atomarer Verbindungszaehler fuer max_verbindungen-Enforcement
         let verbindungszaehler = Arc::new(AtomicUsize::new(0));
         let max_verbindungen = self.konfig.max_verbindungen;
+        let mut verbindungen = JoinSet::new();
 
-        loop {
-            let (stream, peer_addr) = listener.accept().await?;
+        let ergebnis = loop {
+            let (stream, peer_addr) = tokio::select! {
+                angenommen = listener.accept() => match angenommen {
+                    Ok(angenommen) => angenommen,
+                    Err(e) => break Err(e.into()),
+                },
+                _ = beendet(shutdown_rx.clone()) => break Ok(()),
+            };
+            // Beendete Verbindungen einsammeln
+            while verbindungen.try_join_next().is_some() {}
 
             // Connection-Limit pruefen BEVOR TLS-Handshake
             let aktuelle = verbindungszaehler.fetch_add(1, Ordering::SeqCst);
@@ -89,44 +141,75 @@ impl TcpServer {
                 continue;
             }
 
-            let tls_acceptor = tls_acceptor.clone();
+            let quelle = quelle.clone();
             let state = state.clone();
             let rate_limiter = Arc::clone(&rate_limiter);
             let zaehler = Arc::clone(&verbindungszaehler);
-            let zeilenlimit = self.konfig.zeilenlimit_bytes;
-
-            tokio::spawn(async move {
-                match tls_acceptor.accept(stream).await {
-                    Ok(tls_stream) => {
-                        tracing::debug!(peer = %peer_addr, "Neue TCP/TLS-Verbindung");
-                        verbindung_behandeln(
-                            tls_stream,
-                            peer_addr,
-                            state,
-                            rate_limiter,
-                            zeilenlimit,
-                        )
-                        .await;
+            let shutdown_rx = shutdown_rx.clone();
+
+            verbindungen.spawn(async move {
+                match quelle {
+                    Some(quelle) => {
+                        let handshake = tokio::time::timeout(
+                            HANDSHAKE_ZEITLIMIT,
+                            quelle.acceptor().accept(stream),
+                        );
+                        match handshake.await {
+                            Ok(Ok(tls_stream)) => {
+                                tracing::debug!(peer = %peer_addr, "Neue TCP/TLS-Verbindung");
+                                verbindung_behandeln(
+                                    tls_stream,
+                                    peer_addr,
+                                    state,
+                                    rate_limiter,
+                                    shutdown_rx,
+                                )
+                                .await;
+                            }
+                            Ok(Err(e)) => {
+                                tracing::warn!(peer = %peer_addr, fehler = %e, "TLS-Handshake fehlgeschlagen");
+                            }
+                            Err(_) => {
+                                tracing::debug!(peer = %peer_addr, "TLS-Handshake: Zeitlimit ueberschritten");
+                            }
+                        }
                     }
-                    Err(e) => {
-                        tracing::warn!(peer = %peer_addr, fehler = %e, "TLS-Handshake fehlgeschlagen");
+                    None => {
+                        tracing::debug!(peer = %peer_addr, "Neue TCP-Verbindung");
+                        verbindung_behandeln(stream, peer_addr, state, rate_limiter, shutdown_rx)
+                            .await;
                     }
                 }
                 // Verbindungszaehler nach Abschluss dekrementieren
                 zaehler.fetch_sub(1, Ordering::SeqCst);
             });
+        };
+
+        if let Some(nachladen) = nachladen {
+            nachladen.abort();
         }
+        drop(listener);
+        while verbindungen.join_next().await.is_some() {}
+        tracing::info!("TCP-Commander-Server beendet");
+        ergebnis
     }
 }
 
-/// Behandelt eine einzelne TLS-Verbindung
-async fn verbindung_behandeln(
-    stream: tokio_rustls::server::TlsStream<tokio::net::TcpStream>,
+/// Wartet auf das Shutdown-Signal (ein verworfener Sender zaehlt als Signal)
+async fn beendet(mut shutdown_rx: watch::Receiver<bool>) {
+    let _ = shutdown_rx.wait_for(|beenden| *beenden).await;
+}
+
+/// Behandelt eine einzelne Verbindung (TLS oder unverschluesselt)
+async fn verbindung_behandeln<S>(
+    stream: S,
     peer_addr: SocketAddr,
     state: CommanderState,
     rate_limiter: Arc<RateLimiter>,
-    _zeilenlimit: usize,
-) {
+    shutdown_rx: watch::Receiver<bool>,
+) where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
     let (reader, mut writer) = tokio::io::split(stream);
     let mut buf_reader = BufReader::new(reader);
     let mut session = TcpSession::neu(peer_addr);
@@ -140,7 +223,11 @@ async fn verbindung_behandeln(
     let mut zeile = String::new();
     loop {
         zeile.clear();
-        match buf_reader.read_line(&mut zeile).await {
+        let gelesen = tokio::select! {
+            gelesen = buf_reader.read_line(&mut zeile) => gelesen,
+            _ = beendet(shutdown_rx.clone()) => break,
+        };
+        match gelesen {
             Ok(0) => break,
             Ok(_) => {}
             Err(e) => {
@@ -164,12 +251,13 @@ async fn verbindung_behandeln(
             break;
         }
 
-        if session.zustand == crate::tcp::session::SessionZustand::Beendend {
+        if session.zustand == SessionZustand::Beendend {
             break;
         }
     }
 
-    tracing::debug!(peer = %peer_addr, "TCP/TLS-Verbindung beendet");
+    let _ = writer.shutdown().await;
+    tracing::debug!(peer = %peer_addr, "TCP-Verbindung beendet");
 }
 
 /// Verarbeitet eine einzelne Befehlszeile und gibt die Antwort zurueck
@@ -185,30 +273,38 @@ async fn verarbeite_befehl(
         Err(e) => return fehler_antwort_tcp(e.fehler_code(), &e.to_string()),
     };
 
-    // Sonderbefehle: login, quit
+    // Sonderbefehle: login, quit, help
     match parsed.name.as_str() {
+        // login token=<api-token>
         "login" => {
-            let username = match parsed.param("username") {
-                Some(u) => u.to_string(),
-                None => return fehler_antwort_tcp(1005, "username fehlt"),
-            };
-            let passwort = match parsed.param("password") {
-                Some(p) => p.to_string(),
-                None => return fehler_antwort_tcp(1005, "password fehlt"),
+            let token = match parsed.param("token") {
+                Some(t) => t,
+                None => return fehler_antwort_tcp(1005, "token fehlt"),
             };
-            let token = format!("{username}:{passwort}");
-            match (state.token_validator)(&token) {
+            match (state.token_validator)(token) {
                 Ok(cmd_session) => {
-                    let session_id = uuid::Uuid::new_v4().to_string();
+                    tracing::debug!(
+                        peer = %session.client_addr,
+                        benutzer = %cmd_session.benutzer.username,
+                        "TCP-Session angemeldet"
+                    );
                     session.anmelden(cmd_session);
-                    return ok_antwort(&[("session_token", &session_id)]);
+                    return OK_ZEILE.to_string();
                 }
                 Err(e) => return fehler_antwort_tcp(e.fehler_code(), &e.to_string()),
             }
         }
         "quit" => {
-            session.zustand = crate::tcp::session::SessionZustand::Beendend;
-            return ok_antwort(&[("msg", "bye")]);
+            session.zustand = SessionZustand::Beendend;
+            return OK_ZEILE.to_string();
+        }
+        "help" => {
+            let eintraege: Vec<String> = SITZUNGS_BEFEHLE
+                .iter()
+                .chain(BEFEHLE)
+                .map(|name| eintrag(&[("command", *name)]))
+                .collect();
+            return liste_antwort(&eintraege);
         }
         _ => {}
     }
@@ -242,53 +338,232 @@ async fn verarbeite_befehl(
     }
 }
 
-/// Formatiert eine Response als TCP-Antwortzeile
+/// Formatiert eine Response als TCP-Antwort
 fn format_tcp_response(resp: crate::commands::types::Response) -> String {
     use crate::commands::types::Response;
     match resp {
-        Response::Ok => ok_antwort(&[]),
+        Response::Ok => OK_ZEILE.to_string(),
         Response::ServerInfo(info) => ok_antwort(&[
             ("name", &info.name),
             ("version", &info.version),
             ("clients", &info.aktuelle_clients.to_string()),
             ("maxclients", &info.max_clients.to_string()),
             ("uptime", &info.uptime_secs.to_string()),
+            ("features", &info.features.join(",")),
         ]),
-        Response::KanalListe(kanaele) => {
-            if kanaele.is_empty() {
-                return ok_antwort(&[]);
-            }
-            let eintraege: Vec<String> = kanaele
+        Response::KanalListe(seite) => {
+            let eintraege: Vec<String> = seite
+                .kanaele
                 .iter()
-                .map(|k| {
-                    format!(
-                        "cid={}\\sname={}",
-                        k.id,
-                        crate::tcp::parser::encode_value(&k.name)
-                    )
-                })
+                .map(|k| eintrag(&[("cid", &k.id.to_string()), ("name", &k.name)]))
                 .collect();
-            format!("ok {}\n", eintraege.join("|"))
+            liste_antwort(&eintraege)
         }
-        Response::ClientListe(clients) => {
-            if clients.is_empty() {
-                return ok_antwort(&[]);
-            }
-            let eintraege: Vec<String> = clients
+        Response::ClientListe(seite) => {
+            let eintraege: Vec<String> = seite
+                .clients
                 .iter()
-                .map(|c| {
-                    format!(
-                        "clid={} clname={}",
-                        c.user_id,
-                        crate::tcp::parser::encode_value(&c.username)
-                    )
-                })
+                .map(|c| eintrag(&[("clid", &c.user_id.to_string()), ("clname", &c.username)]))
                 .collect();
-            format!("ok {}\n", eintraege.join("|"))
+            liste_antwort(&eintraege)
         }
         other => match serde_json::to_string(&other) {
-            Ok(json) => format!("ok data={}\n", crate::tcp::parser::encode_value(&json)),
-            Err(_) => ok_antwort(&[]),
+            Ok(json) => ok_antwort(&[("data", &json)]),
+            Err(_) => OK_ZEILE.to_string(),
         },
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::CommanderAuth;
+    use crate::commands::CommandExecutor;
+    use crate::rate_limit::RateLimitKonfig;
+    use crate::rest::{ExecutorFn, TokenValidatorFn};
+    use speakeasy_auth::{ApiTokenStore, AuthService, BanService, PermissionService, SessionStore};
+    use speakeasy_db::models::{KanalbaumGrenzen, NeuerKanal};
+    use speakeasy_db::{einstellungen::ServerEinstellungen, ChannelRepository, SqliteDb};
+    use std::time::Duration;
+    use tokio::net::tcp::OwnedReadHalf;
+    use tokio::net::TcpStream;
+
+    /// Laufender Server ohne TLS mit einem Kanal "Mein Kanal"
+    ///
+    /// Gibt Adresse, Kanal-ID, einen API-Token (nur `cmd:channellist`) und
+    /// den Shutdown-Sender zurueck.
+    async fn starten() -> (
+        SocketAddr,
+        uuid::Uuid,
+        String,
+        watch::Sender<bool>,
+        tokio::task::JoinHandle<()>,
+    ) {
+        let db = Arc::new(SqliteDb::in_memory().await.unwrap());
+        let auth_service = Arc::new(AuthService::neu(
+            Arc::clone(&db),
+            SessionStore::neu(),
+            ApiTokenStore::neu(),
+        ));
+        let operator = auth_service
+            .registrieren("operator", "sicheres-passwort-123")
+            .await
+            .unwrap();
+        let token = auth_service
+            .api_token_erstellen(
+                operator.id,
+                "ServerQuery".into(),
+                vec!["cmd:channellist".into()],
+                None,
+            )
+            .await
+            .unwrap()
+            .token_wert;
+        let kanal = ChannelRepository::create(
+            db.as_ref(),
+            NeuerKanal {
+                name: "Mein Kanal",
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+
+        let executor = CommandExecutor::neu(
+            Arc::clone(&db),
+            Arc::clone(&db),
+            Arc::clone(&db),
+            Arc::clone(&db),
+            Arc::clone(&db),
+            Arc::clone(&db),
+            Arc::clone(&db),
+            Arc::clone(&db),
+            Arc::clone(&db),
+            Arc::clone(&auth_service),
+            PermissionService::neu(Arc::clone(&db)),
+            BanService::neu(Arc::clone(&db)),
+            ServerEinstellungen {
+                name: "ServerQuery".into(),
+                willkommensnachricht: None,
+                max_clients: 32,
+                host_nachricht: None,
+                chat_bearbeitungsfrist_sek: 0,
+            },
+            "0.0.0".into(),
+            KanalbaumGrenzen::default(),
+        );
+        let executor_fn: ExecutorFn = Arc::new(move |cmd, session| {
+            let exec = Arc::clone(&executor);
+            Box::pin(async move { exec.ausfuehren(cmd, &session).await })
+        });
+        let auth = Arc::new(CommanderAuth::neu(auth_service));
+        let token_validator: TokenValidatorFn = Arc::new(move |token: &str| {
+            tokio::task::block_in_place(|| {
+                tokio::runtime::Handle::current().block_on(auth.token_validieren(token))
+            })
+        });
+        let state = CommanderState::neu(executor_fn, token_validator);
+
+        let konfig = TcpServerKonfig {
+            bind_addr: "127.0.0.1:0".parse().unwrap(),
+            ..Default::default()
+        };
+        let (tx, rx) = tokio::sync::oneshot::channel();
+        let server = TcpServer::neu(konfig).bei_bereitschaft(move |addr| {
+            let _ = tx.send(addr);
+        });
+        let (shutdown_tx, shutdown_rx) = watch::channel(false);
+        let handle = tokio::spawn(async move {
+            server
+                .starten(
+                    state,
+                    RateLimiter::neu(RateLimitKonfig::default()),
+                    shutdown_rx,
+                )
+                .await
+                .unwrap();
+        });
+        (rx.await.unwrap(), kanal.id, token, shutdown_tx, handle)
+    }
+
+    /// Liest eine Antwort bis einschliesslich der `error`-Zeile
+    async fn antwort(reader: &mut BufReader<OwnedReadHalf>) -> String {
+        let mut antwort = String::new();
+        loop {
+            let vorher = antwort.len();
+            assert!(reader.read_line(&mut antwort).await.unwrap() > 0);
+            if antwort[vorher..].starts_with("error ") {
+                return antwort;
+            }
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn login_mit_api_token_und_kanalliste() {
+        let (addr, kanal_id, token, _shutdown, _) = starten().await;
+        let (lesen, mut schreiben) = TcpStream::connect(addr).await.unwrap().into_split();
+        let mut reader = BufReader::new(lesen);
+
+        let mut begruessung = String::new();
+        reader.read_line(&mut begruessung).await.unwrap();
+        assert_eq!(begruessung, "TS3\n");
+        reader.read_line(&mut begruessung).await.unwrap();
+
+        schreiben.write_all(b"channellist\n").await.unwrap();
+        assert!(antwort(&mut reader).await.starts_with("error id=1001 "));
+
+        schreiben
+            .write_all(b"login token=ungueltig\n")
+            .await
+            .unwrap();
+        assert!(antwort(&mut reader).await.starts_with("error id=1001 "));
+
+        schreiben
+            .write_all(format!("login token={token}\n").as_bytes())
+            .await
+            .unwrap();
+        assert_eq!(antwort(&mut reader).await, "error id=0 msg=ok\n");
+
+        schreiben.write_all(b"channellist\n").await.unwrap();
+        assert_eq!(
+            antwort(&mut reader).await,
+            format!("cid={kanal_id} name=Mein\\sKanal\nerror id=0 msg=ok\n")
+        );
+
+        // Der Token erlaubt nur die Kanalliste
+        schreiben.write_all(b"serverinfo\n").await.unwrap();
+        assert!(antwort(&mut reader).await.starts_with("error id=1002 "));
+
+        schreiben.write_all(b"help\n").await.unwrap();
+        let hilfe = antwort(&mut reader).await;
+        for befehl in ["login", "serverinfo", "channellist", "clientkick"] {
+            assert!(hilfe.contains(&format!("command={befehl}|")), "{hilfe}");
+        }
+
+        schreiben.write_all(b"quit\n").await.unwrap();
+        assert_eq!(antwort(&mut reader).await, "error id=0 msg=ok\n");
+        let mut rest = String::new();
+        assert_eq!(reader.read_line(&mut rest).await.unwrap(), 0);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn shutdown_schliesst_offene_verbindungen() {
+        let (addr, _, _, shutdown, handle) = starten().await;
+        let stream = TcpStream::connect(addr).await.unwrap();
+        let mut reader = BufReader::new(stream);
+        let mut zeile = String::new();
+        reader.read_line(&mut zeile).await.unwrap();
+
+        shutdown.send(true).unwrap();
+        tokio::time::timeout(Duration::from_secs(5), handle)
+            .await
+            .expect("Server nicht beendet")
+            .unwrap();
+
+        // Rest der Begruessung, dann EOF
+        reader.read_line(&mut zeile).await.unwrap();
+        zeile.clear();
+        assert_eq!(reader.read_line(&mut zeile).await.unwrap(), 0);
+        assert!(TcpStream::connect(addr).await.is_err());
+    }
+}
@@ -0,0 +1,472 @@
+//! TLS fuer REST und gRPC des Commanders, optional mit Client-Zertifikaten
+//!
+//! [`TlsQuelle`] laedt Zertifikat und Schluessel sowie optional ein
+//! CA-Bundle fuer Client-Zertifikate (mTLS). Die Dateien werden regelmaessig
+//! auf Aenderungen geprueft ([`TlsQuelle::beobachten`]): neue Verbindungen
+//! nutzen danach das erneuerte Zertifikat, bestehende bleiben unberuehrt.
+//! Schlaegt das Nachladen fehl (z.B. halb geschriebene Datei), bleibt die
+//! bisherige Konfiguration aktiv.
+//!
+//! [`annehmen`] fuehrt die Handshakes durch und liefert zu jeder Verbindung
+//! die [`ClientIdentitaet`] des verifizierten Client-Zertifikats. Welcher
+//! Commander-Benutzer dahinter steht, legen die
+//! [`ZertifikatsZuordnung`](crate::auth::ZertifikatsZuordnung)en fest.
+
+use std::io;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+use parking_lot::{Mutex, RwLock};
+use rustls::pki_types::{CertificateDer, PrivateKeyDer};
+use rustls::server::WebPkiClientVerifier;
+use rustls::{RootCertStore, ServerConfig};
+use serde::{Deserialize, Serialize};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::mpsc;
+use tokio_rustls::server::TlsStream;
+use tokio_rustls::TlsAcceptor;
+use x509_parser::extensions::GeneralName;
+
+use crate::error::{CommanderError, CommanderResult};
+
+/// Standard-Abstand der Pruefung auf erneuerte Zertifikate
+pub const STANDARD_NACHLADE_INTERVALL: Duration = Duration::from_secs(60);
+
+/// Hoechstdauer eines TLS-Handshakes
+pub(crate) const HANDSHAKE_ZEITLIMIT: Duration = Duration::from_secs(10);
+
+/// Umgang mit Client-Zertifikaten bei gesetztem CA-Bundle
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ClientZertModus {
+    /// Ohne gueltiges Client-Zertifikat scheitert der Handshake
+    #[default]
+    Pflicht,
+    /// Ein vorgelegtes Zertifikat muss gueltig sein, fehlen darf es
+    Optional,
+}
+
+/// TLS-Konfiguration fuer REST- bzw. gRPC-Server
+#[derive(Debug, Clone)]
+pub struct TlsKonfig {
+    /// Server-Zertifikatskette (PEM)
+    pub zertifikat: PathBuf,
+    /// Privater Schluessel (PEM)
+    pub schluessel: PathBuf,
+    /// CA-Bundle fuer Client-Zertifikate (`None` = kein mTLS)
+    pub client_ca: Option<PathBuf>,
+    pub client_modus: ClientZertModus,
+    /// Abstand der Pruefung auf geaenderte Dateien
+    pub nachlade_intervall: Duration,
+}
+
+impl TlsKonfig {
+    pub fn neu(zertifikat: impl Into<PathBuf>, schluessel: impl Into<PathBuf>) -> Self {
+        Self {
+            zertifikat: zertifikat.into(),
+            schluessel: schluessel.into(),
+            client_ca: None,
+            client_modus: ClientZertModus::default(),
+            nachlade_intervall: STANDARD_NACHLADE_INTERVALL,
+        }
+    }
+
+    /// Aktiviert mTLS mit dem gegebenen CA-Bundle
+    pub fn mit_client_ca(mut self, client_ca: impl Into<PathBuf>, modus: ClientZertModus) -> Self {
+        self.client_ca = Some(client_ca.into());
+        self.client_modus = modus;
+        self
+    }
+
+    fn dateien(&self) -> impl Iterator<Item = &Path> {
+        [
+            Some(&self.zertifikat),
+            Some(&self.schluessel),
+            self.client_ca.as_ref(),
+        ]
+        .into_iter()
+        .flatten()
+        .map(PathBuf::as_path)
+    }
+}
+
+/// Identitaet aus einem verifizierten Client-Zertifikat
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClientIdentitaet {
+    /// Common Name des Subjekts
+    pub subjekt: Option<String>,
+    /// Alternative Namen (DNS, E-Mail, URI)
+    pub alt_namen: Vec<String>,
+}
+
+impl ClientIdentitaet {
+    /// Liest Subjekt und alternative Namen aus einem DER-Zertifikat
+    pub fn aus_zertifikat(der: &[u8]) -> Option<Self> {
+        let (_, zertifikat) = x509_parser::parse_x509_certificate(der).ok()?;
+        let subjekt = zertifikat
+            .subject()
+            .iter_common_name()
+            .next()
+            .and_then(|cn| cn.as_str().ok())
+            .map(str::to_string);
+        let mut alt_namen = Vec::new();
+        if let Ok(Some(san)) = zertifikat.subject_alternative_name() {
+            for name in &san.value.general_names {
+                match name {
+                    GeneralName::DNSName(n) | GeneralName::RFC822Name(n) | GeneralName::URI(n) => {
+                        alt_namen.push(n.to_string())
+                    }
+                    _ => {}
+                }
+            }
+        }
+        Some(Self { subjekt, alt_namen })
+    }
+
+    /// Identitaet des vom Client vorgelegten (und verifizierten) Zertifikats
+    pub fn aus_verbindung(stream: &TlsStream<TcpStream>) -> Option<Self> {
+        let (_, verbindung) = stream.get_ref();
+        let zertifikat = verbindung.peer_certificates()?.first()?;
+        Self::aus_zertifikat(zertifikat.as_ref())
+    }
+
+    /// Prueft ob Subjekt oder ein alternativer Name dem Namen entspricht
+    pub fn passt(&self, name: &str) -> bool {
+        self.subjekt.as_deref() == Some(name) || self.alt_namen.iter().any(|n| n == name)
+    }
+}
+
+impl std::fmt::Display for ClientIdentitaet {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match (&self.subjekt, self.alt_namen.first()) {
+            (Some(cn), _) => write!(f, "CN={cn}"),
+            (None, Some(name)) => write!(f, "SAN={name}"),
+            (None, None) => f.write_str("(ohne Namen)"),
+        }
+    }
+}
+
+/// Nachladbare TLS-Serverkonfiguration
+pub struct TlsQuelle {
+    konfig: TlsKonfig,
+    alpn: Vec<Vec<u8>>,
+    aktuell: RwLock<Arc<ServerConfig>>,
+    /// Aenderungszeiten der Dateien beim letzten Laden
+    stand: Mutex<Vec<Option<SystemTime>>>,
+}
+
+impl TlsQuelle {
+    /// Laedt die Dateien; `alpn` legt die angebotenen Protokolle fest
+    pub fn laden(konfig: TlsKonfig, alpn: &[&[u8]]) -> CommanderResult<Self> {
+        let alpn: Vec<Vec<u8>> = alpn.iter().map(|p| p.to_vec()).collect();
+        let stand = aenderungszeiten(&konfig);
+        let config = server_config_bauen(&konfig, &alpn)?;
+        Ok(Self {
+            konfig,
+            alpn,
+            aktuell: RwLock::new(Arc::new(config)),
+            stand: Mutex::new(stand),
+        })
+    }
+
+    /// Acceptor mit der aktuell gueltigen Konfiguration
+    pub fn acceptor(&self) -> TlsAcceptor {
+        TlsAcceptor::from(Arc::clone(&self.aktuell.read()))
+    }
+
+    /// Ist mTLS aktiv (CA-Bundle gesetzt)?
+    pub fn mit_client_zertifikaten(&self) -> bool {
+        self.konfig.client_ca.is_some()
+    }
+
+    /// Laedt die Dateien neu, falls sich eine geaendert hat
+    ///
+    /// Gibt `true` zurueck wenn eine neue Konfiguration aktiv ist. Bei einem
+    /// Fehler bleibt die bisherige aktiv; der Versuch wird beim naechsten
+    /// Aufruf wiederholt.
+    pub fn nachladen(&self) -> CommanderResult<bool> {
+        let neu = aenderungszeiten(&self.konfig);
+        if *self.stand.lock() == neu {
+            return Ok(false);
+        }
+        let config = server_config_bauen(&self.konfig, &self.alpn)?;
+        *self.aktuell.write() = Arc::new(config);
+        *self.stand.lock() = neu;
+        Ok(true)
+    }
+
+    /// Prueft im Abstand von `nachlade_intervall` auf erneuerte Dateien
+    pub async fn beobachten(self: Arc<Self>) {
+        let mut intervall = tokio::time::interval(self.konfig.nachlade_intervall);
+        intervall.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        intervall.tick().await;
+        loop {
+            intervall.tick().await;
+            match self.nachladen() {
+                Ok(true) => tracing::info!(
+                    zertifikat = %self.konfig.zertifikat.display(),
+                    "TLS-Zertifikat neu geladen"
+                ),
+                Ok(false) => {}
+                Err(e) => tracing::warn!(
+                    fehler = %e,
+                    "TLS-Zertifikat konnte nicht neu geladen werden, bisheriges bleibt aktiv"
+                ),
+            }
+        }
+    }
+}
+
+/// Verbindung nach erfolgreichem TLS-Handshake
+pub struct TlsVerbindung {
+    pub stream: TlsStream<TcpStream>,
+    pub peer: SocketAddr,
+    /// Verifiziertes Client-Zertifikat (nur bei mTLS und vorgelegtem Zertifikat)
+    pub identitaet: Option<ClientIdentitaet>,
+}
+
+/// Nimmt Verbindungen an und fuehrt die Handshakes in eigenen Tasks durch
+///
+/// Erfolgreiche Verbindungen gehen an `ziel`. Gescheiterte Handshakes – etwa
+/// ohne Client-Zertifikat im Modus [`ClientZertModus::Pflicht`] – werden
+/// protokolliert und verworfen. Endet erst, wenn `ziel` geschlossen wird.
+pub async fn annehmen(
+    listener: TcpListener,
+    quelle: Arc<TlsQuelle>,
+    ziel: mpsc::Sender<TlsVerbindung>,
+) -> io::Result<()> {
+    loop {
+        let (stream, peer) = tokio::select! {
+            angenommen = listener.accept() => angenommen?,
+            _ = ziel.closed() => return Ok(()),
+        };
+        let acceptor = quelle.acceptor();
+        let ziel = ziel.clone();
+        tokio::spawn(async move {
+            let handshake = tokio::time::timeout(HANDSHAKE_ZEITLIMIT, acceptor.accept(stream));
+            let stream = match handshake.await {
+                Ok(Ok(stream)) => stream,
+                Ok(Err(e)) => {
+                    tracing::debug!(peer = %peer, fehler = %e, "TLS-Handshake fehlgeschlagen");
+                    return;
+                }
+                Err(_) => {
+                    tracing::debug!(peer = %peer, "TLS-Handshake: Zeitlimit ueberschritten");
+                    return;
+                }
+            };
+            let identitaet = ClientIdentitaet::aus_verbindung(&stream);
+            let _ = ziel
+                .send(TlsVerbindung {
+                    stream,
+                    peer,
+                    identitaet,
+                })
+                .await;
+        });
+    }
+}
+
+fn aenderungszeiten(konfig: &TlsKonfig) -> Vec<Option<SystemTime>> {
+    konfig
+        .dateien()
+        .map(|pfad| std::fs::metadata(pfad).and_then(|m| m.modified()).ok())
+        .collect()
+}
+
+fn server_config_bauen(konfig: &TlsKonfig, alpn: &[Vec<u8>]) -> CommanderResult<ServerConfig> {
+    let provider = Arc::new(rustls::crypto::ring::default_provider());
+    let builder = ServerConfig::builder_with_provider(Arc::clone(&provider))
+        .with_safe_default_protocol_versions()
+        .map_err(|e| CommanderError::Tls(e.to_string()))?;
+
+    let builder = match &konfig.client_ca {
+        Some(pfad) => {
+            let mut wurzeln = RootCertStore::empty();
+            for zertifikat in zertifikate_lesen(pfad)? {
+                wurzeln.add(zertifikat).map_err(|e| {
+                    CommanderError::Tls(format!(
+                        "{}: ungueltiges CA-Zertifikat: {e}",
+                        pfad.display()
+                    ))
+                })?;
+            }
+            let verifier = WebPkiClientVerifier::builder_with_provider(Arc::new(wurzeln), provider);
+            let verifier = match konfig.client_modus {
+                ClientZertModus::Pflicht => verifier,
+                ClientZertModus::Optional => verifier.allow_unauthenticated(),
+            }
+            .build()
+            .map_err(|e| CommanderError::Tls(e.to_string()))?;
+            builder.with_client_cert_verifier(verifier)
+        }
+        None => builder.with_no_client_auth(),
+    };
+
+    let mut config = builder
+        .with_single_cert(
+            zertifikate_lesen(&konfig.zertifikat)?,
+            schluessel_lesen(&konfig.schluessel)?,
+        )
+        .map_err(|e| CommanderError::Tls(e.to_string()))?;
+    config.alpn_protocols = alpn.to_vec();
+    Ok(config)
+}
+
+fn zertifikate_lesen(pfad: &Path) -> CommanderResult<Vec<CertificateDer<'static>>> {
+    let pem =
+        std::fs::read(pfad).map_err(|e| CommanderError::Tls(format!("{}: {e}", pfad.display())))?;
+    let zertifikate = rustls_pemfile::certs(&mut pem.as_slice())
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| CommanderError::Tls(format!("{}: {e}", pfad.display())))?;
+    if zertifikate.is_empty() {
+        return Err(CommanderError::Tls(format!(
+            "{}: kein Zertifikat gefunden",
+            pfad.display()
+        )));
+    }
+    Ok(zertifikate)
+}
+
+fn schluessel_lesen(pfad: &Path) -> CommanderResult<PrivateKeyDer<'static>> {
+    let pem =
+        std::fs::read(pfad).map_err(|e| CommanderError::Tls(format!("{}: {e}", pfad.display())))?;
+    rustls_pemfile::private_key(&mut pem.as_slice())
+        .map_err(|e| CommanderError::Tls(format!("{}: {e}", pfad.display())))?
+        .ok_or_else(|| {
+            CommanderError::Tls(format!(
+                "{}: kein privater Schluessel gefunden",
+                pfad.display()
+            ))
+        })
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use rcgen::{BasicConstraints, CertificateParams, IsCa, KeyPair, SanType};
+
+    /// Zertifikate fuer Tests: CA, Server-Zertifikat und Client-Zertifikat
+    pub(crate) struct TestPki {
+        pub verzeichnis: PathBuf,
+        ca: rcgen::Certificate,
+        ca_schluessel: KeyPair,
+    }
+
+    impl TestPki {
+        pub(crate) fn neu() -> Self {
+            let verzeichnis =
+                std::env::temp_dir().join(format!("speakeasy-mtls-{}", uuid::Uuid::new_v4()));
+            std::fs::create_dir_all(&verzeichnis).unwrap();
+            let ca_schluessel = KeyPair::generate().unwrap();
+            let mut params = CertificateParams::new(Vec::<String>::new()).unwrap();
+            params.is_ca = IsCa::Ca(BasicConstraints::Unconstrained);
+            params
+                .distinguished_name
+                .push(rcgen::DnType::CommonName, "Speakeasy Test-CA");
+            let ca = params.self_signed(&ca_schluessel).unwrap();
+            std::fs::write(verzeichnis.join("ca.pem"), ca.pem()).unwrap();
+            Self {
+                verzeichnis,
+                ca,
+                ca_schluessel,
+            }
+        }
+
+        /// Von der CA signiertes Zertifikat; gibt (Zertifikat, Schluessel) als PEM zurueck
+        pub(crate) fn ausstellen(&self, cn: &str, san: &[&str]) -> (String, String) {
+            let schluessel = KeyPair::generate().unwrap();
+            let mut params =
+                CertificateParams::new(san.iter().map(|s| s.to_string()).collect::<Vec<_>>())
+                    .unwrap();
+            params
+                .distinguished_name
+                .push(rcgen::DnType::CommonName, cn);
+            params
+                .subject_alt_names
+                .push(SanType::Rfc822Name("ops@example.org".try_into().unwrap()));
+            let zertifikat = params
+                .signed_by(&schluessel, &self.ca, &self.ca_schluessel)
+                .unwrap();
+            (zertifikat.pem(), schluessel.serialize_pem())
+        }
+
+        /// Schreibt ein Server-Zertifikat fuer localhost und gibt die Konfiguration zurueck
+        pub(crate) fn server_konfig(&self) -> TlsKonfig {
+            let (zertifikat, schluessel) = self.ausstellen("localhost", &["localhost"]);
+            std::fs::write(self.verzeichnis.join("server.pem"), zertifikat).unwrap();
+            std::fs::write(self.verzeichnis.join("server.key"), schluessel).unwrap();
+            TlsKonfig::neu(
+                self.verzeichnis.join("server.pem"),
+                self.verzeichnis.join("server.key"),
+            )
+        }
+
+        pub(crate) fn ca_pfad(&self) -> PathBuf {
+            self.verzeichnis.join("ca.pem")
+        }
+    }
+
+    impl Drop for TestPki {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.verzeichnis);
+        }
+    }
+
+    #[test]
+    fn identitaet_aus_subjekt_und_alt_namen() {
+        let pki = TestPki::neu();
+        let (pem, _) = pki.ausstellen("operator-1", &["ops.example.org"]);
+        let der = rustls_pemfile::certs(&mut pem.as_bytes())
+            .next()
+            .unwrap()
+            .unwrap();
+
+        let identitaet = ClientIdentitaet::aus_zertifikat(der.as_ref()).unwrap();
+        assert_eq!(identitaet.subjekt.as_deref(), Some("operator-1"));
+        assert!(identitaet.passt("operator-1"));
+        assert!(identitaet.passt("ops.example.org"));
+        assert!(identitaet.passt("ops@example.org"));
+        assert!(!identitaet.passt("operator"));
+        assert_eq!(identitaet.to_string(), "CN=operator-1");
+    }
+
+    #[test]
+    fn nachladen_nur_bei_geaenderten_dateien() {
+        let pki = TestPki::neu();
+        let konfig = pki
+            .server_konfig()
+            .mit_client_ca(pki.ca_pfad(), ClientZertModus::Pflicht);
+        let quelle = TlsQuelle::laden(konfig.clone(), &[b"h2"]).unwrap();
+        assert!(quelle.mit_client_zertifikaten());
+        assert!(!quelle.nachladen().unwrap());
+        let vorher = Arc::clone(&quelle.aktuell.read());
+
+        // Defekte Datei: bisherige Konfiguration bleibt aktiv (Stand
+        // zuruecksetzen, die Aenderungszeit ist nicht beliebig fein)
+        std::fs::write(&konfig.zertifikat, "kaputt").unwrap();
+        *quelle.stand.lock() = Vec::new();
+        assert!(quelle.nachladen().is_err());
+        assert!(Arc::ptr_eq(&vorher, &quelle.aktuell.read()));
+
+        // Erneuertes Zertifikat wird uebernommen
+        let (zertifikat, schluessel) = pki.ausstellen("localhost", &["localhost"]);
+        std::fs::write(&konfig.zertifikat, zertifikat).unwrap();
+        std::fs::write(&konfig.schluessel, schluessel).unwrap();
+        *quelle.stand.lock() = Vec::new();
+        assert!(quelle.nachladen().unwrap());
+        assert!(!Arc::ptr_eq(&vorher, &quelle.aktuell.read()));
+        assert_eq!(quelle.aktuell.read().alpn_protocols, vec![b"h2".to_vec()]);
+    }
+
+    #[test]
+    fn fehlende_dateien_melden_tls_fehler() {
+        let konfig = TlsKonfig::neu("/nicht/vorhanden.pem", "/nicht/vorhanden.key");
+        assert!(matches!(
+            TlsQuelle::laden(konfig, &[]),
+            Err(CommanderError::Tls(_))
+        ));
+    }
+}
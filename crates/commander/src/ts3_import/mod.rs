@@ -0,0 +1,775 @@
+//! Import eines TeamSpeak-3-Servers
+//!
+//! Uebernimmt aus einem TS3-Snapshot ([`snapshot`]) den Kanalbaum, die
+//! regulaeren Server-Gruppen samt Berechtigungen ([`zuordnung`]) und die
+//! Identitaeten der Client-Datenbank. Geschrieben wird ueber das
+//! [`ImportRepository`] in drei Abschnitten mit je einer Transaktion:
+//! Gruppen, Kanaele, Benutzer bzw. Einladungen. Schlaegt ein Abschnitt fehl,
+//! bleiben die vorherigen bestehen; der Fehler nennt den Abschnitt.
+//!
+//! Fuer Identitaeten gibt es zwei Strategien:
+//! - [`BenutzerStrategie::Platzhalter`]: Konto mit zufaelligem Passwort, das
+//!   beim ersten Login geaendert werden muss (Zugaenge stehen im Bericht)
+//! - [`BenutzerStrategie::Einladung`]: kein Konto, sondern ein einmaliger
+//!   Einladungscode je Identitaet, der ausserhalb des Servers verteilt wird
+//!
+//! Im Probelauf wird nichts geschrieben; der Bericht zeigt, was angelegt
+//! wuerde und welche Konflikte einen echten Import verhindern.
+
+pub mod snapshot;
+pub mod zuordnung;
+
+use std::collections::{BTreeMap, HashSet};
+
+use serde::Serialize;
+use speakeasy_db::{
+    models::{
+        ImportBenutzer, ImportServerGruppe, KanalTyp, KanalbaumGrenzen, NeueEinladung,
+        VorlagenKnoten, MAX_VORLAGEN_KANALNAME,
+    },
+    ChannelRepository, ImportRepository, ServerGroupRepository, UserRepository,
+};
+use uuid::Uuid;
+
+use crate::error::{CommanderError, CommanderResult};
+use snapshot::{Ts3Kanal, Ts3Snapshot};
+use zuordnung::rechte_uebersetzen;
+
+/// TS3-Gruppentyp fuer regulaere Server-Gruppen
+const TS3_GRUPPE_REGULAER: u8 = 1;
+
+/// Eindeutige ID des eingebauten TS3-Administrators (kein echter Benutzer)
+const TS3_SERVERADMIN: &str = "serveradmin";
+
+/// Wie TS3-Identitaeten uebernommen werden
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BenutzerStrategie {
+    /// Konto mit Platzhalter-Passwort (Wechsel beim ersten Login)
+    Platzhalter,
+    /// Einmaliger Einladungscode je Identitaet
+    Einladung,
+}
+
+impl std::str::FromStr for BenutzerStrategie {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "platzhalter" | "placeholder" => Ok(Self::Platzhalter),
+            "einladung" | "invite" => Ok(Self::Einladung),
+            anderes => Err(format!(
+                "Unbekannte Benutzer-Strategie '{anderes}' (platzhalter|einladung)"
+            )),
+        }
+    }
+}
+
+/// Optionen eines Imports
+#[derive(Debug, Clone)]
+pub struct ImportOptionen {
+    pub strategie: BenutzerStrategie,
+    /// Nur pruefen und berichten, nichts schreiben
+    pub probelauf: bool,
+    /// Ersteller der Einladungscodes (Pflicht bei [`BenutzerStrategie::Einladung`])
+    pub ersteller_id: Option<Uuid>,
+    pub grenzen: KanalbaumGrenzen,
+}
+
+/// Zusammenfassung eines (Probe-)Imports
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ImportBericht {
+    pub probelauf: bool,
+    pub server_name: Option<String>,
+    pub kanaele: usize,
+    pub gruppen: usize,
+    pub benutzer: usize,
+    pub einladungen: usize,
+    /// TS3-Berechtigungen ohne Entsprechung, je Gruppe
+    pub nicht_abbildbar: Vec<NichtAbbildbar>,
+    /// Anpassungen und uebersprungene Daten
+    pub hinweise: Vec<String>,
+    /// Gruende, aus denen ein echter Import scheitern wuerde
+    pub konflikte: Vec<String>,
+    pub zugaenge: Vec<ImportZugang>,
+}
+
+/// Nicht abbildbare Berechtigungen einer Gruppe
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct NichtAbbildbar {
+    pub gruppe: String,
+    pub berechtigungen: Vec<String>,
+}
+
+/// Zugang fuer eine uebernommene Identitaet
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ImportZugang {
+    pub ts3_uid: String,
+    pub username: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub passwort: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub einladungscode: Option<String>,
+}
+
+/// Geplanter Benutzer (Name noch nicht gegen die Datenbank geprueft)
+struct GeplanterBenutzer {
+    uid: String,
+    username: String,
+    sgids: Vec<u64>,
+}
+
+/// Aus dem Snapshot abgeleitete Daten, noch ohne Datenbankzugriff
+struct ImportPlan {
+    wurzeln: Vec<VorlagenKnoten>,
+    gruppen: Vec<(u64, ImportServerGruppe)>,
+    benutzer: Vec<GeplanterBenutzer>,
+    bericht: ImportBericht,
+}
+
+/// Importiert einen TS3-Snapshot
+pub async fn importieren<D>(
+    db: &D,
+    text: &str,
+    optionen: &ImportOptionen,
+) -> CommanderResult<ImportBericht>
+where
+    D: ImportRepository + ChannelRepository + ServerGroupRepository + UserRepository,
+{
+    let snapshot = snapshot::snapshot_parsen(text)
+        .map_err(|e| CommanderError::UngueltigeEingabe(format!("TS3-Snapshot: {e}")))?;
+    let mut plan = plan_erstellen(&snapshot);
+    plan.bericht.probelauf = optionen.probelauf;
+
+    konflikte_pruefen(db, &mut plan, optionen).await?;
+    benutzernamen_vergeben(db, &mut plan).await?;
+    match optionen.strategie {
+        BenutzerStrategie::Platzhalter => plan.bericht.benutzer = plan.benutzer.len(),
+        BenutzerStrategie::Einladung => plan.bericht.einladungen = plan.benutzer.len(),
+    }
+
+    if optionen.probelauf {
+        plan.bericht.zugaenge = plan
+            .benutzer
+            .iter()
+            .map(|b| ImportZugang {
+                ts3_uid: b.uid.clone(),
+                username: b.username.clone(),
+                passwort: None,
+                einladungscode: None,
+            })
+            .collect();
+        return Ok(plan.bericht);
+    }
+    if !plan.bericht.konflikte.is_empty() {
+        return Err(CommanderError::UngueltigeEingabe(format!(
+            "Import abgebrochen: {}",
+            plan.bericht.konflikte.join("; ")
+        )));
+    }
+
+    // Abschnitt 1: Server-Gruppen
+    let neue_gruppen: Vec<ImportServerGruppe> =
+        plan.gruppen.iter().map(|(_, g)| g.clone()).collect();
+    let angelegt = db
+        .server_gruppen_importieren(&neue_gruppen)
+        .await
+        .map_err(|e| abschnitt_fehler("Server-Gruppen", e))?;
+    let gruppen_ids: BTreeMap<u64, Uuid> = plan
+        .gruppen
+        .iter()
+        .zip(&angelegt)
+        .map(|((sgid, _), record)| (*sgid, record.id))
+        .collect();
+
+    // Abschnitt 2: Kanaele
+    db.kanaele_importieren(&plan.wurzeln, optionen.grenzen)
+        .await
+        .map_err(|e| abschnitt_fehler("Kanaele", e))?;
+
+    // Abschnitt 3: Benutzer bzw. Einladungen
+    let gruppen_von = |b: &GeplanterBenutzer| -> Vec<Uuid> {
+        b.sgids
+            .iter()
+            .filter_map(|sgid| gruppen_ids.get(sgid).copied())
+            .collect()
+    };
+    match optionen.strategie {
+        BenutzerStrategie::Platzhalter => {
+            let mut neue = Vec::with_capacity(plan.benutzer.len());
+            for b in &plan.benutzer {
+                let passwort = Uuid::new_v4().simple().to_string()[..16].to_string();
+                neue.push(ImportBenutzer {
+                    username: b.username.clone(),
+                    password_hash: speakeasy_auth::passwort_hashen(&passwort)?,
+                    gruppen: gruppen_von(b),
+                });
+                plan.bericht.zugaenge.push(ImportZugang {
+                    ts3_uid: b.uid.clone(),
+                    username: b.username.clone(),
+                    passwort: Some(passwort),
+                    einladungscode: None,
+                });
+            }
+            db.benutzer_importieren(&neue)
+                .await
+                .map_err(|e| abschnitt_fehler("Benutzer", e))?;
+        }
+        BenutzerStrategie::Einladung => {
+            let ersteller = optionen.ersteller_id.ok_or_else(|| {
+                CommanderError::UngueltigeEingabe("Einladungen benoetigen einen Ersteller".into())
+            })?;
+            let codes: Vec<String> = plan
+                .benutzer
+                .iter()
+                .map(|_| speakeasy_auth::invite_code_generieren())
+                .collect();
+            let mut neue = Vec::with_capacity(plan.benutzer.len());
+            for (b, code) in plan.benutzer.iter().zip(&codes) {
+                let gruppen = gruppen_von(b);
+                if gruppen.len() > 1 {
+                    plan.bericht.hinweise.push(format!(
+                        "Einladung fuer '{}' vergibt nur die erste von {} Gruppen",
+                        b.username,
+                        gruppen.len()
+                    ));
+                }
+                neue.push(NeueEinladung {
+                    code,
+                    channel_id: None,
+                    assigned_group_id: gruppen.first().copied(),
+                    max_uses: 1,
+                    expires_at: None,
+                    created_by: ersteller,
+                });
+                plan.bericht.zugaenge.push(ImportZugang {
+                    ts3_uid: b.uid.clone(),
+                    username: b.username.clone(),
+                    passwort: None,
+                    einladungscode: Some(code.clone()),
+                });
+            }
+            db.einladungen_importieren(&neue)
+                .await
+                .map_err(|e| abschnitt_fehler("Einladungen", e))?;
+        }
+    }
+
+    Ok(plan.bericht)
+}
+
+fn abschnitt_fehler(abschnitt: &str, e: speakeasy_db::DbError) -> CommanderError {
+    CommanderError::UngueltigeEingabe(format!("Import-Abschnitt {abschnitt}: {e}"))
+}
+
+/// Leitet Kanalbaum, Gruppen und Benutzer aus dem Snapshot ab
+fn plan_erstellen(snapshot: &Ts3Snapshot) -> ImportPlan {
+    let mut bericht = ImportBericht {
+        server_name: snapshot.server_name.clone(),
+        ..Default::default()
+    };
+
+    let wurzeln = kanalbaum_bauen(&snapshot.kanaele, &mut bericht.hinweise);
+    bericht.kanaele = wurzeln.iter().map(VorlagenKnoten::anzahl_kanaele).sum();
+
+    let mut gruppen = Vec::new();
+    for gruppe in &snapshot.gruppen {
+        if gruppe.typ != TS3_GRUPPE_REGULAER {
+            bericht.hinweise.push(format!(
+                "Gruppe '{}' uebersprungen (keine regulaere Server-Gruppe)",
+                gruppe.name
+            ));
+            continue;
+        }
+        if gruppe.name.trim().is_empty() {
+            bericht
+                .hinweise
+                .push(format!("Gruppe {} ohne Namen uebersprungen", gruppe.sgid));
+            continue;
+        }
+        let uebersetzung = rechte_uebersetzen(&gruppe.rechte);
+        if !uebersetzung.nicht_abbildbar.is_empty() {
+            bericht.nicht_abbildbar.push(NichtAbbildbar {
+                gruppe: gruppe.name.clone(),
+                berechtigungen: uebersetzung.nicht_abbildbar,
+            });
+        }
+        gruppen.push((
+            gruppe.sgid,
+            ImportServerGruppe {
+                name: gruppe.name.trim().to_string(),
+                priority: gruppe.sortid,
+                berechtigungen: uebersetzung.berechtigungen,
+            },
+        ));
+    }
+    bericht.gruppen = gruppen.len();
+
+    let mut benutzer = Vec::new();
+    for client in &snapshot.clients {
+        if client.uid == TS3_SERVERADMIN {
+            continue;
+        }
+        let name = client.nickname.trim();
+        let username = if name.is_empty() {
+            format!("ts3-{}", client.cldbid)
+        } else {
+            name.to_string()
+        };
+        let sgids = gruppen
+            .iter()
+            .map(|(sgid, _)| *sgid)
+            .filter(|sgid| {
+                snapshot
+                    .gruppen
+                    .iter()
+                    .any(|g| g.sgid == *sgid && g.mitglieder.contains(&client.cldbid))
+            })
+            .collect();
+        benutzer.push(GeplanterBenutzer {
+            uid: client.uid.clone(),
+            username,
+            sgids,
+        });
+    }
+
+    ImportPlan {
+        wurzeln,
+        gruppen,
+        benutzer,
+        bericht,
+    }
+}
+
+/// Baut den Kanalbaum in TS3-Reihenfolge
+fn kanalbaum_bauen(kanaele: &[Ts3Kanal], hinweise: &mut Vec<String>) -> Vec<VorlagenKnoten> {
+    let ids: HashSet<u64> = kanaele.iter().map(|k| k.id).collect();
+    let mut kinder: BTreeMap<u64, Vec<&Ts3Kanal>> = BTreeMap::new();
+    for kanal in kanaele {
+        let pid = if kanal.pid != 0 && !ids.contains(&kanal.pid) {
+            hinweise.push(format!(
+                "Kanal '{}' verweist auf unbekannten Eltern-Kanal {} und wird Wurzel",
+                kanal.name, kanal.pid
+            ));
+            0
+        } else {
+            kanal.pid
+        };
+        kinder.entry(pid).or_default().push(kanal);
+    }
+
+    let mut besucht = HashSet::new();
+    let wurzeln = knoten_bauen(0, &kinder, &mut besucht, hinweise);
+    if besucht.len() < ids.len() {
+        hinweise.push(format!(
+            "{} Kanaele mit zyklischer Eltern-Beziehung uebersprungen",
+            ids.len() - besucht.len()
+        ));
+    }
+    wurzeln
+}
+
+fn knoten_bauen(
+    pid: u64,
+    kinder: &BTreeMap<u64, Vec<&Ts3Kanal>>,
+    besucht: &mut HashSet<u64>,
+    hinweise: &mut Vec<String>,
+) -> Vec<VorlagenKnoten> {
+    let Some(geschwister) = kinder.get(&pid) else {
+        return Vec::new();
+    };
+    let mut knoten = Vec::new();
+    for kanal in geschwister_sortieren(geschwister) {
+        if !besucht.insert(kanal.id) {
+            continue;
+        }
+        let mut name = kanal.name.trim().to_string();
+        if name.is_empty() {
+            name = format!("Kanal {}", kanal.id);
+        }
+        if name.chars().count() > MAX_VORLAGEN_KANALNAME {
+            hinweise.push(format!(
+                "Kanalname '{name}' auf {MAX_VORLAGEN_KANALNAME} Zeichen gekuerzt"
+            ));
+            name = name.chars().take(MAX_VORLAGEN_KANALNAME).collect();
+        }
+        if kanal.hat_passwort {
+            hinweise.push(format!(
+                "Passwort von Kanal '{name}' nicht uebernommen (nur als Hash exportiert)"
+            ));
+        }
+        knoten.push(VorlagenKnoten {
+            name,
+            channel_type: KanalTyp::Voice,
+            topic: kanal.topic.clone(),
+            max_clients: kanal.max_clients,
+            codec_profile: None,
+            permissions: vec![],
+            children: knoten_bauen(kanal.id, kinder, besucht, hinweise),
+        });
+    }
+    knoten
+}
+
+/// Sortiert Geschwister nach der TS3-Verkettung (`channel_order` = Vorgaenger)
+///
+/// Nicht erreichbare Kanaele (kaputte Verkettung) folgen nach ID sortiert.
+fn geschwister_sortieren<'a>(geschwister: &[&'a Ts3Kanal]) -> Vec<&'a Ts3Kanal> {
+    let mut sortiert = Vec::with_capacity(geschwister.len());
+    let mut vorgaenger = 0;
+    while let Some(naechster) = geschwister
+        .iter()
+        .find(|k| k.order == vorgaenger && !sortiert.iter().any(|s: &&Ts3Kanal| s.id == k.id))
+    {
+        sortiert.push(*naechster);
+        vorgaenger = naechster.id;
+    }
+    let mut rest: Vec<&Ts3Kanal> = geschwister
+        .iter()
+        .filter(|k| !sortiert.iter().any(|s| s.id == k.id))
+        .copied()
+        .collect();
+    rest.sort_by_key(|k| k.id);
+    sortiert.extend(rest);
+    sortiert
+}
+
+/// Sammelt Konflikte mit dem bestehenden Datenbestand
+async fn konflikte_pruefen<D>(
+    db: &D,
+    plan: &mut ImportPlan,
+    optionen: &ImportOptionen,
+) -> CommanderResult<()>
+where
+    D: ChannelRepository + ServerGroupRepository,
+{
+    let konflikte = &mut plan.bericht.konflikte;
+
+    let vorhandene: HashSet<String> = ServerGroupRepository::list(db)
+        .await?
+        .into_iter()
+        .map(|g| g.name)
+        .collect();
+    for (_, gruppe) in &plan.gruppen {
+        if vorhandene.contains(&gruppe.name) {
+            konflikte.push(format!("Server-Gruppe '{}' existiert bereits", gruppe.name));
+        }
+    }
+
+    let grenzen = optionen.grenzen;
+    let tiefe = plan
+        .wurzeln
+        .iter()
+        .map(VorlagenKnoten::tiefe)
+        .max()
+        .unwrap_or(0);
+    if grenzen.max_tiefe > 0 && tiefe > grenzen.max_tiefe {
+        konflikte.push(format!(
+            "Verschachtelungstiefe {tiefe} ueberschreitet das Limit von {}",
+            grenzen.max_tiefe
+        ));
+    }
+    let gesamt = ChannelRepository::list(db).await?.len() + plan.bericht.kanaele;
+    if grenzen.max_kanaele > 0 && gesamt > grenzen.max_kanaele as usize {
+        konflikte.push(format!(
+            "Kanal-Limit ueberschritten: {gesamt} > {}",
+            grenzen.max_kanaele
+        ));
+    }
+
+    if optionen.strategie == BenutzerStrategie::Einladung && optionen.ersteller_id.is_none() {
+        konflikte.push("Einladungen benoetigen einen Ersteller".into());
+    }
+    Ok(())
+}
+
+/// Macht Benutzernamen eindeutig (untereinander und gegenueber bestehenden)
+async fn benutzernamen_vergeben<D>(db: &D, plan: &mut ImportPlan) -> CommanderResult<()>
+where
+    D: UserRepository,
+{
+    let mut vergeben: HashSet<String> = HashSet::new();
+    for b in &mut plan.benutzer {
+        let basis = b.username.clone();
+        let mut nr = 1;
+        loop {
+            let kandidat = if nr == 1 {
+                basis.clone()
+            } else {
+                format!("{basis}-{nr}")
+            };
+            if !vergeben.contains(&kandidat.to_lowercase())
+                && db.get_by_name(&kandidat).await?.is_none()
+            {
+                if nr > 1 {
+                    plan.bericht.hinweise.push(format!(
+                        "Benutzername '{basis}' bereits vergeben, verwende '{kandidat}'"
+                    ));
+                }
+                vergeben.insert(kandidat.to_lowercase());
+                b.username = kandidat;
+                break;
+            }
+            nr += 1;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use speakeasy_db::{
+        models::{BerechtigungsWert, BerechtigungsZiel, KanalRecord, NeuerBenutzer, TriState},
+        InviteRepository, PermissionRepository, SqliteDb,
+    };
+
+    const SNAPSHOT: &str = include_str!("testdaten/ts3_snapshot.txt");
+
+    fn optionen(strategie: BenutzerStrategie, probelauf: bool) -> ImportOptionen {
+        ImportOptionen {
+            strategie,
+            probelauf,
+            ersteller_id: None,
+            grenzen: KanalbaumGrenzen::default(),
+        }
+    }
+
+    fn kanal<'a>(kanaele: &'a [KanalRecord], name: &str) -> &'a KanalRecord {
+        kanaele
+            .iter()
+            .find(|k| k.name == name)
+            .unwrap_or_else(|| panic!("Kanal '{name}' fehlt"))
+    }
+
+    async fn gruppen_rechte(db: &SqliteDb, name: &str) -> Vec<(String, BerechtigungsWert)> {
+        let gruppe = ServerGroupRepository::list(db)
+            .await
+            .unwrap()
+            .into_iter()
+            .find(|g| g.name == name)
+            .unwrap_or_else(|| panic!("Gruppe '{name}' fehlt"));
+        let mut rechte = db
+            .get_permissions(&BerechtigungsZiel::ServerGruppe(gruppe.id), None)
+            .await
+            .unwrap();
+        rechte.sort_by(|a, b| a.0.cmp(&b.0));
+        rechte
+    }
+
+    fn grant(key: &str) -> (String, BerechtigungsWert) {
+        (key.into(), BerechtigungsWert::TriState(TriState::Grant))
+    }
+
+    #[tokio::test]
+    async fn probelauf_berichtet_ohne_zu_schreiben() {
+        let db = SqliteDb::in_memory().await.unwrap();
+        let kanaele_vorher = ChannelRepository::list(&db).await.unwrap().len();
+
+        let bericht = importieren(
+            &db,
+            SNAPSHOT,
+            &optionen(BenutzerStrategie::Platzhalter, true),
+        )
+        .await
+        .unwrap();
+
+        assert!(bericht.probelauf);
+        assert_eq!(bericht.server_name.as_deref(), Some("Gilde der Nacht"));
+        assert_eq!(bericht.kanaele, 5);
+        assert_eq!(bericht.gruppen, 2);
+        assert_eq!(bericht.benutzer, 3);
+        assert!(bericht.konflikte.is_empty());
+        assert_eq!(
+            bericht.nicht_abbildbar,
+            vec![
+                NichtAbbildbar {
+                    gruppe: "Server Admin".into(),
+                    berechtigungen: vec!["i_client_max_avatar_filesize".into()],
+                },
+                NichtAbbildbar {
+                    gruppe: "Normal".into(),
+                    berechtigungen: vec!["b_client_info_view".into()],
+                },
+            ]
+        );
+        assert!(bericht.hinweise.iter().any(|h| h.contains("'AFK'")));
+        assert!(bericht
+            .hinweise
+            .iter()
+            .any(|h| h.contains("Guest Server Query")));
+        let namen: Vec<&str> = bericht
+            .zugaenge
+            .iter()
+            .map(|z| z.username.as_str())
+            .collect();
+        assert_eq!(namen, ["alice", "bob", "alice-2"]);
+        assert!(bericht.zugaenge.iter().all(|z| z.passwort.is_none()));
+
+        assert_eq!(
+            ChannelRepository::list(&db).await.unwrap().len(),
+            kanaele_vorher
+        );
+        assert!(ServerGroupRepository::list(&db).await.unwrap().is_empty());
+        assert!(db.get_by_name("alice").await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn import_uebernimmt_baum_gruppen_und_benutzer() {
+        let db = SqliteDb::in_memory().await.unwrap();
+
+        let bericht = importieren(
+            &db,
+            SNAPSHOT,
+            &optionen(BenutzerStrategie::Platzhalter, false),
+        )
+        .await
+        .unwrap();
+        assert!(!bericht.probelauf);
+
+        // Kanalbaum in TS3-Reihenfolge
+        let kanaele = ChannelRepository::list(&db).await.unwrap();
+        let lobby = kanal(&kanaele, "Lobby");
+        let spiele = kanal(&kanaele, "Spiele");
+        let afk = kanal(&kanaele, "AFK");
+        let raid1 = kanal(&kanaele, "Raid 1");
+        let raid2 = kanal(&kanaele, "Raid 2");
+        assert!(lobby.parent_id.is_none());
+        assert!(lobby.sort_order < spiele.sort_order && spiele.sort_order < afk.sort_order);
+        assert_eq!(lobby.topic.as_deref(), Some("Willkommen auf dem Server"));
+        assert_eq!(lobby.max_clients, 0);
+        assert!(afk.password_hash.is_none());
+        assert_eq!(raid1.parent_id, Some(spiele.id));
+        assert_eq!(raid2.parent_id, Some(spiele.id));
+        assert!(raid1.sort_order < raid2.sort_order);
+        assert_eq!((raid1.max_clients, raid2.max_clients), (25, 10));
+
+        // Gruppen mit uebersetzten Berechtigungen
+        assert_eq!(
+            gruppen_rechte(&db, "Server Admin").await,
+            vec![
+                grant("b_channel_create"),
+                grant("b_client_kick_server"),
+                grant("b_server_modify"),
+                (
+                    "i_client_move_power".into(),
+                    BerechtigungsWert::IntLimit(75)
+                ),
+            ]
+        );
+        assert_eq!(
+            gruppen_rechte(&db, "Normal").await,
+            vec![grant("b_channel_join")]
+        );
+
+        // Benutzer mit Platzhalter-Passwort und Gruppen
+        let zugang = &bericht.zugaenge[0];
+        let alice = db.get_by_name("alice").await.unwrap().unwrap();
+        assert!(!alice.password_changed);
+        assert!(speakeasy_auth::passwort_verifizieren(
+            zugang.passwort.as_deref().unwrap(),
+            &alice.password_hash
+        )
+        .unwrap());
+        let mut gruppen: Vec<String> = db
+            .list_for_user(alice.id)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|g| g.name)
+            .collect();
+        gruppen.sort();
+        assert_eq!(gruppen, ["Normal", "Server Admin"]);
+        assert!(db.get_by_name("alice-2").await.unwrap().is_some());
+        assert!(db.get_by_name("serveradmin").await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn einladungen_statt_konten() {
+        let db = SqliteDb::in_memory().await.unwrap();
+        let ersteller = UserRepository::create(
+            &db,
+            NeuerBenutzer {
+                username: "admin",
+                password_hash: "hash",
+            },
+        )
+        .await
+        .unwrap();
+
+        let ohne_ersteller = importieren(
+            &db,
+            SNAPSHOT,
+            &optionen(BenutzerStrategie::Einladung, false),
+        )
+        .await;
+        assert!(ohne_ersteller.is_err());
+
+        let bericht = importieren(
+            &db,
+            SNAPSHOT,
+            &ImportOptionen {
+                ersteller_id: Some(ersteller.id),
+                ..optionen(BenutzerStrategie::Einladung, false)
+            },
+        )
+        .await
+        .unwrap();
+        assert_eq!((bericht.benutzer, bericht.einladungen), (0, 3));
+        assert!(db.get_by_name("alice").await.unwrap().is_none());
+
+        let bob = bericht
+            .zugaenge
+            .iter()
+            .find(|z| z.username == "bob")
+            .unwrap();
+        let einladung = db
+            .get_by_code(bob.einladungscode.as_deref().unwrap())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(einladung.max_uses, 1);
+        assert_eq!(einladung.created_by, ersteller.id);
+        let normal = ServerGroupRepository::list(&db)
+            .await
+            .unwrap()
+            .into_iter()
+            .find(|g| g.name == "Normal")
+            .unwrap();
+        assert_eq!(einladung.assigned_group_id, Some(normal.id));
+    }
+
+    #[tokio::test]
+    async fn konflikte_verhindern_jeden_schreibzugriff() {
+        let db = SqliteDb::in_memory().await.unwrap();
+        importieren(
+            &db,
+            SNAPSHOT,
+            &optionen(BenutzerStrategie::Platzhalter, false),
+        )
+        .await
+        .unwrap();
+        let kanaele = ChannelRepository::list(&db).await.unwrap().len();
+
+        // Zweiter Lauf: Gruppennamen existieren bereits
+        let bericht = importieren(
+            &db,
+            SNAPSHOT,
+            &optionen(BenutzerStrategie::Platzhalter, true),
+        )
+        .await
+        .unwrap();
+        assert_eq!(bericht.konflikte.len(), 2);
+        assert_eq!(bericht.zugaenge[0].username, "alice-3");
+
+        let err = importieren(
+            &db,
+            SNAPSHOT,
+            &optionen(BenutzerStrategie::Platzhalter, false),
+        )
+        .await
+        .unwrap_err();
+        assert!(matches!(err, CommanderError::UngueltigeEingabe(_)));
+        assert_eq!(ChannelRepository::list(&db).await.unwrap().len(), kanaele);
+    }
+}
@@ -0,0 +1,246 @@
+//! Parser fuer TS3-Server-Snapshots
+//!
+//! Gelesen wird das Klartextformat von `serversnapshotcreate` bzw. eine
+//! ServerQuery-Ausgabe gleicher Form: Datensaetze sind durch `|` oder
+//! Zeilenumbrueche getrennt, Felder durch Leerzeichen (`key=wert`), Werte
+//! sind ServerQuery-escaped (`\s` = Leerzeichen, `\p` = `|`, ...).
+//!
+//! Die Art eines Datensatzes ergibt sich aus seinen Feldern:
+//!
+//! | Felder                               | Bedeutung                              |
+//! |--------------------------------------|----------------------------------------|
+//! | `virtualserver_*`                    | Server-Einstellungen                   |
+//! | `channel_id`                         | Kanal                                  |
+//! | `sgid` + `name`                      | Beginn einer Server-Gruppe             |
+//! | `permsid` + `permvalue`              | Berechtigung der offenen Gruppe        |
+//! | `end_group`                          | Ende der offenen Gruppe                |
+//! | `sgid` + `cldbid`                    | Gruppenmitgliedschaft                  |
+//! | `client_unique_identifier`           | Identitaet (Client-Datenbankeintrag)   |
+//!
+//! Unbekannte Datensaetze und Abschnittsmarker (`end_*`) werden uebersprungen.
+
+use std::collections::BTreeMap;
+
+/// Inhalt eines TS3-Snapshots
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Ts3Snapshot {
+    pub server_name: Option<String>,
+    pub kanaele: Vec<Ts3Kanal>,
+    pub gruppen: Vec<Ts3Gruppe>,
+    pub clients: Vec<Ts3Client>,
+}
+
+/// Ein TS3-Kanal
+#[derive(Debug, Clone, PartialEq)]
+pub struct Ts3Kanal {
+    pub id: u64,
+    /// Eltern-Kanal (0 = Wurzel)
+    pub pid: u64,
+    pub name: String,
+    pub topic: Option<String>,
+    /// Maximale Clients (0 = unbegrenzt)
+    pub max_clients: i64,
+    /// Kanal direkt oberhalb unter denselben Eltern (0 = erster)
+    pub order: u64,
+    pub hat_passwort: bool,
+}
+
+/// Eine TS3-Server-Gruppe
+#[derive(Debug, Clone, PartialEq)]
+pub struct Ts3Gruppe {
+    pub sgid: u64,
+    pub name: String,
+    /// 0 = Vorlage, 1 = regulaer, 2 = ServerQuery
+    pub typ: u8,
+    pub sortid: i64,
+    pub rechte: Vec<Ts3Recht>,
+    /// Client-Datenbank-IDs der Mitglieder
+    pub mitglieder: Vec<u64>,
+}
+
+/// Eine TS3-Berechtigung
+#[derive(Debug, Clone, PartialEq)]
+pub struct Ts3Recht {
+    pub name: String,
+    pub wert: i64,
+}
+
+/// Eine TS3-Identitaet aus der Client-Datenbank
+#[derive(Debug, Clone, PartialEq)]
+pub struct Ts3Client {
+    pub cldbid: u64,
+    pub uid: String,
+    pub nickname: String,
+}
+
+/// Hebt das ServerQuery-Escaping eines Werts auf
+pub fn entschluesseln(wert: &str) -> String {
+    let mut ergebnis = String::with_capacity(wert.len());
+    let mut zeichen = wert.chars();
+    while let Some(c) = zeichen.next() {
+        if c != '\\' {
+            ergebnis.push(c);
+            continue;
+        }
+        match zeichen.next() {
+            Some('s') => ergebnis.push(' '),
+            Some('p') => ergebnis.push('|'),
+            Some('/') => ergebnis.push('/'),
+            Some('\\') => ergebnis.push('\\'),
+            Some('n') => ergebnis.push('\n'),
+            Some('r') => ergebnis.push('\r'),
+            Some('t') => ergebnis.push('\t'),
+            Some('a') => ergebnis.push('\u{07}'),
+            Some('b') => ergebnis.push('\u{08}'),
+            Some('f') => ergebnis.push('\u{0C}'),
+            Some('v') => ergebnis.push('\u{0B}'),
+            Some(anderes) => {
+                ergebnis.push('\\');
+                ergebnis.push(anderes);
+            }
+            None => ergebnis.push('\\'),
+        }
+    }
+    ergebnis
+}
+
+/// Liest einen Snapshot
+///
+/// Gibt bei ungueltigen Zahlenwerten oder Berechtigungen ausserhalb einer
+/// Gruppe eine Fehlermeldung zurueck.
+pub fn snapshot_parsen(text: &str) -> Result<Ts3Snapshot, String> {
+    let mut snapshot = Ts3Snapshot::default();
+    let mut offene_gruppe: Option<Ts3Gruppe> = None;
+    let mut mitgliedschaften: Vec<(u64, u64)> = Vec::new();
+
+    for datensatz in text.split(['|', '\n', '\r']) {
+        let felder = felder_lesen(datensatz);
+        if felder.is_empty() {
+            continue;
+        }
+
+        if felder.contains_key("end_group") {
+            snapshot.gruppen.extend(offene_gruppe.take());
+        } else if felder.contains_key("permsid") {
+            let gruppe = offene_gruppe.as_mut().ok_or_else(|| {
+                format!(
+                    "Berechtigung '{}' ausserhalb einer Gruppe",
+                    feld(&felder, "permsid")
+                )
+            })?;
+            gruppe.rechte.push(Ts3Recht {
+                name: feld(&felder, "permsid").to_string(),
+                wert: zahl(&felder, "permvalue")?.unwrap_or(0),
+            });
+        } else if felder.contains_key("channel_id") {
+            snapshot.kanaele.push(kanal_lesen(&felder)?);
+        } else if felder.contains_key("sgid") && felder.contains_key("cldbid") {
+            mitgliedschaften.push((
+                zahl(&felder, "sgid")?.unwrap_or(0),
+                zahl(&felder, "cldbid")?.unwrap_or(0),
+            ));
+        } else if felder.contains_key("sgid") && felder.contains_key("name") {
+            snapshot.gruppen.extend(offene_gruppe.take());
+            offene_gruppe = Some(Ts3Gruppe {
+                sgid: zahl(&felder, "sgid")?.unwrap_or(0),
+                name: feld(&felder, "name").to_string(),
+                typ: zahl(&felder, "type")?.unwrap_or(1),
+                sortid: zahl(&felder, "sortid")?.unwrap_or(0),
+                rechte: Vec::new(),
+                mitglieder: Vec::new(),
+            });
+        } else if felder.contains_key("client_unique_identifier") {
+            let cldbid = match zahl(&felder, "client_database_id")? {
+                Some(id) => id,
+                None => zahl(&felder, "cldbid")?.unwrap_or(0),
+            };
+            snapshot.clients.push(Ts3Client {
+                cldbid,
+                uid: feld(&felder, "client_unique_identifier").to_string(),
+                nickname: feld(&felder, "client_nickname").to_string(),
+            });
+        } else if let Some(name) = felder.get("virtualserver_name") {
+            snapshot.server_name = Some(name.clone());
+        }
+    }
+    snapshot.gruppen.extend(offene_gruppe);
+
+    // Mitgliedschaften unbekannter Gruppen werden verworfen
+    for (sgid, cldbid) in mitgliedschaften {
+        if let Some(gruppe) = snapshot.gruppen.iter_mut().find(|g| g.sgid == sgid) {
+            gruppe.mitglieder.push(cldbid);
+        }
+    }
+
+    Ok(snapshot)
+}
+
+fn felder_lesen(datensatz: &str) -> BTreeMap<String, String> {
+    datensatz
+        .split_whitespace()
+        .map(|token| match token.split_once('=') {
+            Some((key, wert)) => (key.to_string(), entschluesseln(wert)),
+            None => (token.to_string(), String::new()),
+        })
+        .collect()
+}
+
+fn feld<'a>(felder: &'a BTreeMap<String, String>, key: &str) -> &'a str {
+    felder.get(key).map(String::as_str).unwrap_or("")
+}
+
+fn zahl<T: std::str::FromStr>(
+    felder: &BTreeMap<String, String>,
+    key: &str,
+) -> Result<Option<T>, String> {
+    match felder.get(key) {
+        None => Ok(None),
+        Some(wert) if wert.is_empty() => Ok(None),
+        Some(wert) => wert
+            .parse()
+            .map(Some)
+            .map_err(|_| format!("Ungueltiger Wert fuer '{key}': {wert}")),
+    }
+}
+
+fn kanal_lesen(felder: &BTreeMap<String, String>) -> Result<Ts3Kanal, String> {
+    let unbegrenzt = feld(felder, "channel_flag_maxclients_unlimited") == "1";
+    let max_clients = zahl(felder, "channel_maxclients")?.unwrap_or(-1);
+    let topic = feld(felder, "channel_topic");
+    Ok(Ts3Kanal {
+        id: zahl(felder, "channel_id")?.unwrap_or(0),
+        pid: zahl(felder, "channel_pid")?.unwrap_or(0),
+        name: feld(felder, "channel_name").to_string(),
+        topic: (!topic.is_empty()).then(|| topic.to_string()),
+        max_clients: if unbegrenzt || max_clients < 0 {
+            0
+        } else {
+            max_clients
+        },
+        order: zahl(felder, "channel_order")?.unwrap_or(0),
+        hat_passwort: feld(felder, "channel_flag_password") == "1",
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escaping_wird_aufgehoben() {
+        assert_eq!(entschluesseln(r"Raum\s1\p2\/3\\4"), r"Raum 1|2/3\4");
+        assert_eq!(entschluesseln(r"Zeile\nneu"), "Zeile\nneu");
+    }
+
+    #[test]
+    fn berechtigung_ohne_gruppe_ist_fehler() {
+        let err = snapshot_parsen("permsid=b_channel_create_temporary permvalue=1").unwrap_err();
+        assert!(err.contains("ausserhalb einer Gruppe"));
+    }
+
+    #[test]
+    fn ungueltige_zahl_ist_fehler() {
+        let err = snapshot_parsen("channel_id=abc channel_name=Lobby").unwrap_err();
+        assert!(err.contains("channel_id"));
+    }
+}
@@ -0,0 +1,243 @@
+//! Zuordnung von TS3-Berechtigungen auf den Speakeasy-Katalog
+//!
+//! TS3 kennt deutlich feinere Berechtigungen (z.B. getrennte Rechte fuer
+//! permanente, semi-permanente und temporaere Kanaele). Mehrere TS3-Keys
+//! koennen daher auf denselben Speakeasy-Key fallen; sie werden vereinigt
+//! (ein Grant genuegt, bei Limits gilt das hoechste). Keys ohne Eintrag in
+//! [`ZUORDNUNG`] landen im Bericht als nicht abbildbar.
+
+use std::collections::BTreeMap;
+
+use speakeasy_db::models::{BerechtigungsWert, TriState};
+
+use super::snapshot::Ts3Recht;
+
+/// Wie ein TS3-Wert uebertragen wird
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Abbildung {
+    /// Boolescher TS3-Wert: `1` wird zu Grant
+    Schalter,
+    /// TS3-Power-Wert: jeder Wert > 0 wird zu Grant
+    AbSchwelle,
+    /// TS3-Power-Wert wird als Limit uebernommen
+    Leistung,
+}
+
+/// Ein Eintrag der Zuordnungstabelle
+#[derive(Debug, Clone, Copy)]
+pub struct Zuordnung {
+    /// TS3-Key; ein abschliessendes `*` steht fuer beliebige Endungen
+    pub ts3: &'static str,
+    /// Speakeasy-Key aus dem Berechtigungskatalog
+    pub speakeasy: &'static str,
+    pub abbildung: Abbildung,
+}
+
+const fn z(ts3: &'static str, speakeasy: &'static str, abbildung: Abbildung) -> Zuordnung {
+    Zuordnung {
+        ts3,
+        speakeasy,
+        abbildung,
+    }
+}
+
+/// Zuordnungstabelle TS3 -> Speakeasy
+pub const ZUORDNUNG: &[Zuordnung] = &[
+    z(
+        "b_virtualserver_modify_*",
+        "b_server_modify",
+        Abbildung::Schalter,
+    ),
+    z("b_virtualserver_stop", "b_server_stop", Abbildung::Schalter),
+    z(
+        "b_channel_create_*",
+        "b_channel_create",
+        Abbildung::Schalter,
+    ),
+    z(
+        "b_channel_delete_*",
+        "b_channel_delete",
+        Abbildung::Schalter,
+    ),
+    z(
+        "b_channel_modify_*",
+        "b_channel_modify",
+        Abbildung::Schalter,
+    ),
+    z("b_channel_join_*", "b_channel_join", Abbildung::Schalter),
+    z(
+        "i_channel_join_power",
+        "b_channel_join",
+        Abbildung::AbSchwelle,
+    ),
+    z(
+        "i_client_move_power",
+        "i_client_move_power",
+        Abbildung::Leistung,
+    ),
+    z(
+        "i_client_needed_move_power",
+        "i_client_needed_move_power",
+        Abbildung::Leistung,
+    ),
+    z(
+        "i_client_kick_from_server_power",
+        "b_client_kick_server",
+        Abbildung::AbSchwelle,
+    ),
+    z(
+        "i_client_kick_from_channel_power",
+        "b_client_kick_channel",
+        Abbildung::AbSchwelle,
+    ),
+    z(
+        "i_client_ban_power",
+        "b_client_ban_server",
+        Abbildung::AbSchwelle,
+    ),
+    z(
+        "i_client_poke_power",
+        "b_client_poke",
+        Abbildung::AbSchwelle,
+    ),
+    z(
+        "i_permission_modify_power",
+        "b_permission_modify",
+        Abbildung::AbSchwelle,
+    ),
+    z(
+        "b_virtualserver_servergroup_permission_list",
+        "b_permission_view",
+        Abbildung::Schalter,
+    ),
+    z(
+        "b_virtualserver_channelgroup_permission_list",
+        "b_permission_view",
+        Abbildung::Schalter,
+    ),
+    z(
+        "b_client_permissionoverview_view",
+        "b_permission_read",
+        Abbildung::Schalter,
+    ),
+    z(
+        "i_client_talk_power",
+        "b_voice_transmit",
+        Abbildung::AbSchwelle,
+    ),
+];
+
+/// Sucht den Tabelleneintrag fuer einen TS3-Key
+pub fn zuordnung_fuer(ts3_key: &str) -> Option<&'static Zuordnung> {
+    ZUORDNUNG.iter().find(|z| match z.ts3.strip_suffix('*') {
+        Some(praefix) => ts3_key.starts_with(praefix),
+        None => z.ts3 == ts3_key,
+    })
+}
+
+/// Ergebnis der Uebersetzung einer Gruppe
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Uebersetzung {
+    /// Speakeasy-Berechtigungen, nach Key sortiert
+    pub berechtigungen: Vec<(String, BerechtigungsWert)>,
+    /// TS3-Keys ohne Entsprechung
+    pub nicht_abbildbar: Vec<String>,
+}
+
+/// Uebersetzt die Berechtigungen einer TS3-Gruppe
+///
+/// Werte <= 0 erzeugen keine Regel; die Gruppe erbt dann wie in TS3 den
+/// Standard.
+pub fn rechte_uebersetzen(rechte: &[Ts3Recht]) -> Uebersetzung {
+    let mut werte: BTreeMap<&'static str, BerechtigungsWert> = BTreeMap::new();
+    let mut nicht_abbildbar = Vec::new();
+
+    for recht in rechte {
+        let Some(zuordnung) = zuordnung_fuer(&recht.name) else {
+            nicht_abbildbar.push(recht.name.clone());
+            continue;
+        };
+        if recht.wert <= 0 {
+            continue;
+        }
+        match zuordnung.abbildung {
+            Abbildung::Schalter | Abbildung::AbSchwelle => {
+                werte.insert(
+                    zuordnung.speakeasy,
+                    BerechtigungsWert::TriState(TriState::Grant),
+                );
+            }
+            Abbildung::Leistung => {
+                let limit = match werte.get(zuordnung.speakeasy) {
+                    Some(BerechtigungsWert::IntLimit(bisher)) => (*bisher).max(recht.wert),
+                    _ => recht.wert,
+                };
+                werte.insert(zuordnung.speakeasy, BerechtigungsWert::IntLimit(limit));
+            }
+        }
+    }
+
+    nicht_abbildbar.sort();
+    nicht_abbildbar.dedup();
+    Uebersetzung {
+        berechtigungen: werte
+            .into_iter()
+            .map(|(key, wert)| (key.to_string(), wert))
+            .collect(),
+        nicht_abbildbar,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use speakeasy_db::permissions::BERECHTIGUNGS_KATALOG;
+
+    fn recht(name: &str, wert: i64) -> Ts3Recht {
+        Ts3Recht {
+            name: name.into(),
+            wert,
+        }
+    }
+
+    #[test]
+    fn alle_ziele_stehen_im_katalog() {
+        for z in ZUORDNUNG {
+            assert!(
+                BERECHTIGUNGS_KATALOG.contains(&z.speakeasy),
+                "{} -> {} fehlt im Katalog",
+                z.ts3,
+                z.speakeasy
+            );
+        }
+    }
+
+    #[test]
+    fn mehrere_keys_werden_vereinigt() {
+        let ergebnis = rechte_uebersetzen(&[
+            recht("b_channel_create_temporary", 0),
+            recht("b_channel_create_permanent", 1),
+            recht("i_client_move_power", 50),
+            recht("i_client_move_power", 75),
+            recht("i_client_max_avatar_filesize", 1000),
+            recht("i_client_max_avatar_filesize", 2000),
+        ]);
+        assert_eq!(
+            ergebnis.berechtigungen,
+            vec![
+                (
+                    "b_channel_create".to_string(),
+                    BerechtigungsWert::TriState(TriState::Grant)
+                ),
+                (
+                    "i_client_move_power".to_string(),
+                    BerechtigungsWert::IntLimit(75)
+                ),
+            ]
+        );
+        assert_eq!(
+            ergebnis.nicht_abbildbar,
+            vec!["i_client_max_avatar_filesize"]
+        );
+    }
+}
@@ -0,0 +1,484 @@
+//! Zeitplaner fuer geplante Aktionen
+//!
+//! Prueft einmal pro Minute, welche geplanten Aktionen faellig sind, und
+//! fuehrt sie ueber [`CommanderState::ausfuehren`] aus – mit derselben
+//! Session (Benutzer und Scopes), mit der die Aktion angelegt wurde. Rechte,
+//! Audit-Log und Broadcasts verhalten sich damit wie bei einem manuellen
+//! Befehl.
+//!
+//! Jede Ausfuehrung wird vorher in der Datenbank beansprucht
+//! ([`ZeitplanRepository::claim`]): eine Aktion laeuft hoechstens einmal pro
+//! Termin, auch wenn der Server mitten im Zeitfenster neu startet. Stuerzt
+//! der Server zwischen Beanspruchen und Ausfuehren ab, entfaellt dieser
+//! Termin.
+//!
+//! Beim Start werden verpasste Termine einmal nachgeholt, sofern sie nicht
+//! aelter als die Verfallszeit sind. Von mehreren verpassten Terminen einer
+//! wiederkehrenden Aktion wird nur einer ausgefuehrt.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use speakeasy_db::{
+    models::{GeplanteAktion, GeplanteAktionRecord, NeueGeplanteAktion},
+    repository::{UserRepository, ZeitplanRepository},
+    zeitplan::Zeitplan,
+};
+use tokio::sync::watch;
+
+use crate::auth::{AuthArt, CommanderSession};
+use crate::commands::types::{Command, Response};
+use crate::error::CommanderResult;
+use crate::rest::CommanderState;
+
+/// Abstand zwischen zwei Durchlaeufen
+pub const PRUEF_INTERVALL: Duration = Duration::from_secs(60);
+
+/// Standard: verpasste Termine, die aelter sind, werden verworfen
+pub const STANDARD_VERFALLSZEIT: Duration = Duration::from_secs(60 * 60);
+
+/// Quelle der aktuellen Zeit (in Tests austauschbar)
+pub trait Uhr: Send + Sync {
+    fn jetzt(&self) -> DateTime<Utc>;
+}
+
+/// Systemuhr
+pub struct SystemUhr;
+
+impl Uhr for SystemUhr {
+    fn jetzt(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+/// Fuehrt faellige geplante Aktionen aus
+pub struct Zeitplaner<Z: ZeitplanRepository, U: UserRepository> {
+    zeitplan_repo: Arc<Z>,
+    user_repo: Arc<U>,
+    commander: CommanderState,
+    uhr: Arc<dyn Uhr>,
+    verfallszeit: chrono::Duration,
+}
+
+impl<Z: ZeitplanRepository, U: UserRepository> Zeitplaner<Z, U> {
+    pub fn neu(
+        zeitplan_repo: Arc<Z>,
+        user_repo: Arc<U>,
+        commander: CommanderState,
+        uhr: Arc<dyn Uhr>,
+        verfallszeit: Duration,
+    ) -> Self {
+        Self {
+            zeitplan_repo,
+            user_repo,
+            commander,
+            uhr,
+            verfallszeit: chrono::Duration::from_std(verfallszeit).unwrap_or(chrono::Duration::MAX),
+        }
+    }
+
+    /// Laeuft bis zum Shutdown; der erste Durchlauf erfolgt sofort
+    /// (verpasste Termine nachholen)
+    pub async fn laufen(&self, mut shutdown_rx: watch::Receiver<bool>) {
+        let mut takt = tokio::time::interval(PRUEF_INTERVALL);
+        takt.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+
+        loop {
+            tokio::select! {
+                _ = takt.tick() => {
+                    if let Err(e) = self.durchlauf().await {
+                        tracing::error!(fehler = %e, "Zeitplaner-Durchlauf fehlgeschlagen");
+                    }
+                }
+                _ = shutdown_rx.wait_for(|beenden| *beenden) => return,
+            }
+        }
+    }
+
+    /// Fuehrt alle faelligen Aktionen aus und gibt deren Anzahl zurueck
+    pub async fn durchlauf(&self) -> CommanderResult<usize> {
+        let jetzt = self.uhr.jetzt();
+        let mut ausgefuehrt = 0;
+
+        for aktion in self.zeitplan_repo.due(jetzt).await? {
+            let Some(geplant) = aktion.next_run_at else {
+                continue;
+            };
+            // Naechster Termin ab jetzt: weitere verpasste Termine entfallen
+            let naechste = aktion.zeitplan.naechste_ausfuehrung(jetzt);
+            if !self
+                .zeitplan_repo
+                .claim(aktion.id, geplant, naechste, jetzt)
+                .await?
+            {
+                // Bereits ausgefuehrt (z.B. vor einem Neustart) oder abgebrochen
+                continue;
+            }
+
+            if jetzt - geplant > self.verfallszeit {
+                tracing::warn!(
+                    zeitplan_id = %aktion.id,
+                    name = %aktion.name,
+                    geplant = %geplant,
+                    "Verpasster Termin ist veraltet und wird uebersprungen"
+                );
+                continue;
+            }
+
+            if self.ausfuehren(&aktion, geplant).await {
+                ausgefuehrt += 1;
+            }
+        }
+        Ok(ausgefuehrt)
+    }
+
+    /// Fuehrt eine beanspruchte Aktion aus; Fehler werden nur protokolliert
+    async fn ausfuehren(&self, aktion: &GeplanteAktionRecord, geplant: DateTime<Utc>) -> bool {
+        let benutzer = match self.user_repo.get_by_id(aktion.created_by).await {
+            Ok(Some(b)) if b.is_active => b,
+            Ok(_) => {
+                tracing::warn!(
+                    zeitplan_id = %aktion.id,
+                    benutzer_id = %aktion.created_by,
+                    "Ersteller fehlt oder ist deaktiviert, Aktion wird nicht ausgefuehrt"
+                );
+                return false;
+            }
+            Err(e) => {
+                tracing::error!(zeitplan_id = %aktion.id, fehler = %e, "Ersteller nicht ladbar");
+                return false;
+            }
+        };
+        let session = CommanderSession {
+            benutzer,
+            scopes: aktion.scopes.clone().unwrap_or_default(),
+            auth_art: match aktion.scopes {
+                Some(_) => AuthArt::ApiToken,
+                None => AuthArt::Session,
+            },
+        };
+
+        // Einzelne Schritte laufen auch weiter, wenn ein vorheriger scheitert
+        // (z.B. ein bereits manuell geloeschter Kanal)
+        let mut erfolgreich = true;
+        for cmd in Command::aus_geplanter_aktion(&aktion.aktion) {
+            match self.commander.ausfuehren(cmd, session.clone()).await {
+                Ok(Response::Kanalbaum(kanaele)) => {
+                    if let GeplanteAktion::KanalAusVorlage {
+                        loeschen_nach_min: Some(minuten),
+                        ..
+                    } = &aktion.aktion
+                    {
+                        // Unterkanaele zuerst, die Wurzel zuletzt
+                        let kanal_ids = kanaele.iter().rev().map(|k| k.id).collect();
+                        self.loeschen_planen(aktion, kanal_ids, geplant, *minuten)
+                            .await;
+                    }
+                }
+                Ok(_) => {}
+                Err(e) => {
+                    tracing::warn!(
+                        zeitplan_id = %aktion.id,
+                        name = %aktion.name,
+                        fehler = %e,
+                        "Geplante Aktion fehlgeschlagen"
+                    );
+                    erfolgreich = false;
+                }
+            }
+        }
+        if erfolgreich {
+            tracing::info!(
+                zeitplan_id = %aktion.id,
+                name = %aktion.name,
+                typ = aktion.aktion.typ(),
+                "Geplante Aktion ausgefuehrt"
+            );
+        }
+        erfolgreich
+    }
+
+    /// Plant das Loeschen zeitlich begrenzter Kanaele
+    async fn loeschen_planen(
+        &self,
+        aktion: &GeplanteAktionRecord,
+        kanal_ids: Vec<uuid::Uuid>,
+        geplant: DateTime<Utc>,
+        minuten: u32,
+    ) {
+        let ende = geplant + chrono::Duration::minutes(minuten as i64);
+        let name = format!("{} (Ende)", aktion.name);
+        let ergebnis = self
+            .zeitplan_repo
+            .create(NeueGeplanteAktion {
+                name: &name,
+                aktion: GeplanteAktion::KanaeleLoeschen { kanal_ids },
+                zeitplan: Zeitplan::Einmalig(ende),
+                next_run_at: ende,
+                created_by: aktion.created_by,
+                scopes: aktion.scopes.as_deref(),
+            })
+            .await;
+        if let Err(e) = ergebnis {
+            tracing::error!(
+                zeitplan_id = %aktion.id,
+                fehler = %e,
+                "Loeschen der zeitlich begrenzten Kanaele konnte nicht geplant werden"
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    use speakeasy_auth::{ApiTokenStore, AuthService, BanService, PermissionService, SessionStore};
+    use speakeasy_db::{
+        einstellungen::ServerEinstellungen,
+        models::{KanalbaumGrenzen, NeuerBenutzer},
+        ChannelRepository, SqliteDb,
+    };
+    use uuid::Uuid;
+
+    use crate::commands::CommandExecutor;
+    use crate::error::CommanderError;
+    use crate::rest::{ExecutorFn, TokenValidatorFn};
+
+    /// Mitgelieferte Vorlage "Team-Kategorie" (5 Kanaele)
+    const TEAM_VORLAGE: Uuid = Uuid::from_u128(0x00000000_0000_4000_8000_00000000a001);
+
+    struct TestUhr(Mutex<DateTime<Utc>>);
+
+    impl TestUhr {
+        fn stellen(&self, text: &str) {
+            *self.0.lock().unwrap() = utc(text);
+        }
+    }
+
+    impl Uhr for TestUhr {
+        fn jetzt(&self) -> DateTime<Utc> {
+            *self.0.lock().unwrap()
+        }
+    }
+
+    fn utc(text: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(text)
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    async fn commander(db: &Arc<SqliteDb>) -> CommanderState {
+        let executor = CommandExecutor::neu(
+            Arc::clone(db),
+            Arc::clone(db),
+            Arc::clone(db),
+            Arc::clone(db),
+            Arc::clone(db),
+            Arc::clone(db),
+            Arc::clone(db),
+            Arc::clone(db),
+            Arc::clone(db),
+            Arc::new(AuthService::neu(
+                Arc::clone(db),
+                SessionStore::neu(),
+                ApiTokenStore::neu(),
+            )),
+            PermissionService::neu(Arc::clone(db)),
+            BanService::neu(Arc::clone(db)),
+            ServerEinstellungen {
+                name: "Test".into(),
+                willkommensnachricht: None,
+                max_clients: 32,
+                host_nachricht: None,
+                chat_bearbeitungsfrist_sek: 0,
+            },
+            "0.0.0".into(),
+            KanalbaumGrenzen::default(),
+        );
+        let executor_fn: ExecutorFn = Arc::new(move |cmd, session| {
+            let exec = Arc::clone(&executor);
+            Box::pin(async move { exec.ausfuehren(cmd, &session).await })
+        });
+        let validator: TokenValidatorFn =
+            Arc::new(|_| Err(CommanderError::Authentifizierung("unbenutzt".into())));
+        CommanderState::neu(executor_fn, validator)
+    }
+
+    async fn zeitplaner_starten(
+        db: &Arc<SqliteDb>,
+        uhr: &Arc<TestUhr>,
+    ) -> Zeitplaner<SqliteDb, SqliteDb> {
+        let uhr: Arc<dyn Uhr> = uhr.clone();
+        Zeitplaner::neu(
+            Arc::clone(db),
+            Arc::clone(db),
+            commander(db).await,
+            uhr,
+            STANDARD_VERFALLSZEIT,
+        )
+    }
+
+    async fn benutzer(db: &SqliteDb) -> Uuid {
+        UserRepository::create(
+            db,
+            NeuerBenutzer {
+                username: "planer",
+                password_hash: "hash",
+            },
+        )
+        .await
+        .unwrap()
+        .id
+    }
+
+    async fn anzahl_kanaele(db: &SqliteDb) -> usize {
+        ChannelRepository::list(db).await.unwrap().len()
+    }
+
+    #[tokio::test]
+    async fn wiederholung_feuert_und_neustart_fuehrt_nicht_doppelt_aus() {
+        let db = Arc::new(SqliteDb::in_memory().await.unwrap());
+        let uhr = Arc::new(TestUhr(Mutex::new(utc("2026-10-16T17:59:30Z"))));
+        let user = benutzer(&db).await;
+
+        // Freitags 20:00 Berlin (18:00 UTC), Kanaele nach 3 Stunden wieder weg
+        let zeitplan = Zeitplan::wiederkehrend("0 20 * * 5", "Europe/Berlin").unwrap();
+        let erste = zeitplan.erste_ausfuehrung(uhr.jetzt()).unwrap();
+        assert_eq!(erste, utc("2026-10-16T18:00:00Z"));
+        let aktion = ZeitplanRepository::create(
+            db.as_ref(),
+            NeueGeplanteAktion {
+                name: "Freitags-Event",
+                aktion: GeplanteAktion::KanalAusVorlage {
+                    vorlage_id: TEAM_VORLAGE,
+                    parent_id: None,
+                    name_prefix: Some("Event: ".into()),
+                    loeschen_nach_min: Some(180),
+                },
+                zeitplan,
+                next_run_at: erste,
+                created_by: user,
+                scopes: None,
+            },
+        )
+        .await
+        .unwrap();
+        let kanaele_vorher = anzahl_kanaele(&db).await;
+
+        let planer = zeitplaner_starten(&db, &uhr).await;
+        assert_eq!(planer.durchlauf().await.unwrap(), 0);
+
+        uhr.stellen("2026-10-16T18:00:20Z");
+        assert_eq!(planer.durchlauf().await.unwrap(), 1);
+        assert_eq!(anzahl_kanaele(&db).await, kanaele_vorher + 5);
+
+        // Neustart mitten im Zeitfenster: neue Instanz, gleiche Datenbank
+        drop(planer);
+        let planer = zeitplaner_starten(&db, &uhr).await;
+        uhr.stellen("2026-10-16T18:00:40Z");
+        assert_eq!(planer.durchlauf().await.unwrap(), 0);
+        assert_eq!(anzahl_kanaele(&db).await, kanaele_vorher + 5);
+
+        let gespeichert = ZeitplanRepository::get_by_id(db.as_ref(), aktion.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(gespeichert.next_run_at, Some(utc("2026-10-23T18:00:00Z")));
+
+        // Nach drei Stunden wird der Event-Kanal samt Unterkanaelen geloescht
+        uhr.stellen("2026-10-16T21:00:05Z");
+        assert_eq!(planer.durchlauf().await.unwrap(), 1);
+        assert_eq!(anzahl_kanaele(&db).await, kanaele_vorher);
+
+        // Eine Woche spaeter erneut
+        uhr.stellen("2026-10-23T18:00:05Z");
+        assert_eq!(planer.durchlauf().await.unwrap(), 1);
+        assert_eq!(anzahl_kanaele(&db).await, kanaele_vorher + 5);
+    }
+
+    #[tokio::test]
+    async fn verpasste_termine_mit_verfallszeit() {
+        let db = Arc::new(SqliteDb::in_memory().await.unwrap());
+        let uhr = Arc::new(TestUhr(Mutex::new(utc("2026-10-16T12:00:00Z"))));
+        let user = benutzer(&db).await;
+
+        let mut ids = Vec::new();
+        for (name, zeitpunkt) in [
+            ("kurz verpasst", "2026-10-16T11:50:00Z"),
+            ("lange verpasst", "2026-10-16T09:00:00Z"),
+        ] {
+            let geplant = utc(zeitpunkt);
+            let aktion = ZeitplanRepository::create(
+                db.as_ref(),
+                NeueGeplanteAktion {
+                    name,
+                    aktion: GeplanteAktion::KanalAusVorlage {
+                        vorlage_id: TEAM_VORLAGE,
+                        parent_id: None,
+                        name_prefix: Some(format!("{name}: ")),
+                        loeschen_nach_min: None,
+                    },
+                    zeitplan: Zeitplan::Einmalig(geplant),
+                    next_run_at: geplant,
+                    created_by: user,
+                    scopes: None,
+                },
+            )
+            .await
+            .unwrap();
+            ids.push(aktion.id);
+        }
+        let kanaele_vorher = anzahl_kanaele(&db).await;
+
+        // Start nach Ausfallzeit: nur der Termin innerhalb der Verfallszeit laeuft
+        let planer = zeitplaner_starten(&db, &uhr).await;
+        assert_eq!(planer.durchlauf().await.unwrap(), 1);
+        assert_eq!(anzahl_kanaele(&db).await, kanaele_vorher + 5);
+        assert_eq!(planer.durchlauf().await.unwrap(), 0);
+
+        for id in ids {
+            let aktion = ZeitplanRepository::get_by_id(db.as_ref(), id)
+                .await
+                .unwrap()
+                .unwrap();
+            assert_eq!(aktion.next_run_at, None);
+        }
+    }
+
+    #[tokio::test]
+    async fn ausfuehrung_mit_den_scopes_des_erstellers() {
+        let db = Arc::new(SqliteDb::in_memory().await.unwrap());
+        let uhr = Arc::new(TestUhr(Mutex::new(utc("2026-10-16T12:00:00Z"))));
+        let user = benutzer(&db).await;
+
+        // Token ohne "cmd:channelcreate": die Ausfuehrung wird abgewiesen
+        let scopes = vec!["admin:schedule".to_string()];
+        let geplant = utc("2026-10-16T11:59:00Z");
+        ZeitplanRepository::create(
+            db.as_ref(),
+            NeueGeplanteAktion {
+                name: "ohne Recht",
+                aktion: GeplanteAktion::KanalAusVorlage {
+                    vorlage_id: TEAM_VORLAGE,
+                    parent_id: None,
+                    name_prefix: None,
+                    loeschen_nach_min: None,
+                },
+                zeitplan: Zeitplan::Einmalig(geplant),
+                next_run_at: geplant,
+                created_by: user,
+                scopes: Some(&scopes),
+            },
+        )
+        .await
+        .unwrap();
+        let kanaele_vorher = anzahl_kanaele(&db).await;
+
+        let planer = zeitplaner_starten(&db, &uhr).await;
+        assert_eq!(planer.durchlauf().await.unwrap(), 0);
+        assert_eq!(anzahl_kanaele(&db).await, kanaele_vorher);
+    }
+}
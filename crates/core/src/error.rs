@@ -1,13 +1,374 @@
 //! Fehlertypen fuer Speakeasy
 //!
 //! Zentraler Fehler-Enum der alle moeglichen Fehlerzustaende abdeckt.
-//! Untermodule koennen eigene Fehler definieren und via `#[from]` konvertieren.
+//! Untermodule definieren eigene Fehler und bilden sie per `From` auf
+//! [`SpeakeasyError`] ab; die spezifische Variante bleibt dabei erhalten.
+//!
+//! Jede Variante traegt einen stabilen [`FehlerCode`] (Nummer und Name,
+//! gruppiert nach [`FehlerBereich`]). Alle benutzernahen Schichten
+//! (Signaling-`ErrorResponse`, REST, gRPC, Tauri) leiten Code und Meldung
+//! daraus ab, statt eigene Zuordnungen zu pflegen. Nummern und Namen
+//! duerfen nie geaendert oder neu vergeben werden.
 
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
 /// Globaler Result-Alias fuer Speakeasy
 pub type Result<T> = std::result::Result<T, SpeakeasyError>;
 
+/// Fachbereich eines Fehlercodes (Tausenderstelle der Nummer)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FehlerBereich {
+    Auth,
+    Berechtigung,
+    Kanal,
+    Chat,
+    Datei,
+    Voice,
+    Datenbank,
+    Anfrage,
+    Intern,
+}
+
+impl FehlerBereich {
+    /// Stabiler Name des Bereichs (Praefix der Code-Namen)
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Auth => "auth",
+            Self::Berechtigung => "permission",
+            Self::Kanal => "channel",
+            Self::Chat => "chat",
+            Self::Datei => "file",
+            Self::Voice => "voice",
+            Self::Datenbank => "db",
+            Self::Anfrage => "request",
+            Self::Intern => "internal",
+        }
+    }
+}
+
+/// Stabiler Fehlercode
+///
+/// Serialisiert als Name (z.B. `"channel.full"`); die Nummer steht fuer
+/// Protokolle mit numerischen Codes zur Verfuegung.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum FehlerCode {
+    // --- Auth (1xxx) ---
+    #[serde(rename = "auth.invalid_credentials")]
+    UngueltigeAnmeldedaten,
+    #[serde(rename = "auth.session_invalid")]
+    SessionUngueltig,
+    #[serde(rename = "auth.token_invalid")]
+    TokenUngueltig,
+    #[serde(rename = "auth.account_locked")]
+    KontoGesperrt,
+    #[serde(rename = "auth.banned")]
+    Gebannt,
+    #[serde(rename = "auth.username_taken")]
+    BenutzernameVergeben,
+    #[serde(rename = "auth.invite_invalid")]
+    EinladungUngueltig,
+    #[serde(rename = "auth.user_not_found")]
+    BenutzerNichtGefunden,
+    #[serde(rename = "auth.registration_disabled")]
+    RegistrierungDeaktiviert,
+
+    // --- Berechtigung (2xxx) ---
+    #[serde(rename = "permission.denied")]
+    ZugriffVerweigert,
+    #[serde(rename = "permission.scope_missing")]
+    ScopeFehlt,
+
+    // --- Kanal (3xxx) ---
+    #[serde(rename = "channel.not_found")]
+    KanalNichtGefunden,
+    #[serde(rename = "channel.full")]
+    KanalVoll,
+    #[serde(rename = "channel.password_required")]
+    KanalPasswort,
+
+    // --- Chat (4xxx) ---
+    #[serde(rename = "chat.message_not_found")]
+    NachrichtNichtGefunden,
+    #[serde(rename = "chat.edit_window_expired")]
+    FristAbgelaufen,
+
+    // --- Datei (5xxx) ---
+    #[serde(rename = "file.not_found")]
+    DateiNichtGefunden,
+    #[serde(rename = "file.too_large")]
+    DateiZuGross,
+    #[serde(rename = "file.quota_exceeded")]
+    KontingentErschoepft,
+    #[serde(rename = "file.storage_failed")]
+    Speicher,
+
+    // --- Voice (6xxx) ---
+    #[serde(rename = "voice.codec_unavailable")]
+    Codec,
+    #[serde(rename = "voice.crypto_failed")]
+    Krypto,
+    #[serde(rename = "voice.audio_failed")]
+    Audio,
+
+    // --- Datenbank (7xxx) ---
+    #[serde(rename = "db.conflict")]
+    Konflikt,
+    #[serde(rename = "db.failed")]
+    Datenbank,
+
+    // --- Anfrage (8xxx) ---
+    #[serde(rename = "request.invalid")]
+    UngueltigeAnfrage,
+    #[serde(rename = "request.unsupported_version")]
+    ProtokollVersion,
+    #[serde(rename = "request.not_found")]
+    NichtGefunden,
+    #[serde(rename = "request.rate_limited")]
+    RateLimit,
+    #[serde(rename = "request.timeout")]
+    Zeitlimit,
+    #[serde(rename = "request.server_full")]
+    ServerVoll,
+    #[serde(rename = "request.connection_failed")]
+    Verbindung,
+
+    // --- Intern (9xxx) ---
+    #[serde(rename = "internal")]
+    Intern,
+    #[serde(rename = "internal.config")]
+    Konfiguration,
+    #[serde(rename = "internal.plugin")]
+    Plugin,
+}
+
+impl FehlerCode {
+    /// Alle Codes (fuer Tests und Dokumentation)
+    pub const ALLE: &'static [FehlerCode] = &[
+        Self::UngueltigeAnmeldedaten,
+        Self::SessionUngueltig,
+        Self::TokenUngueltig,
+        Self::KontoGesperrt,
+        Self::Gebannt,
+        Self::BenutzernameVergeben,
+        Self::EinladungUngueltig,
+        Self::BenutzerNichtGefunden,
+        Self::RegistrierungDeaktiviert,
+        Self::ZugriffVerweigert,
+        Self::ScopeFehlt,
+        Self::KanalNichtGefunden,
+        Self::KanalVoll,
+        Self::KanalPasswort,
+        Self::NachrichtNichtGefunden,
+        Self::FristAbgelaufen,
+        Self::DateiNichtGefunden,
+        Self::DateiZuGross,
+        Self::KontingentErschoepft,
+        Self::Speicher,
+        Self::Codec,
+        Self::Krypto,
+        Self::Audio,
+        Self::Konflikt,
+        Self::Datenbank,
+        Self::UngueltigeAnfrage,
+        Self::ProtokollVersion,
+        Self::NichtGefunden,
+        Self::RateLimit,
+        Self::Zeitlimit,
+        Self::ServerVoll,
+        Self::Verbindung,
+        Self::Intern,
+        Self::Konfiguration,
+        Self::Plugin,
+    ];
+
+    /// Stabile Nummer (Tausenderstelle = Bereich)
+    pub fn nummer(self) -> u32 {
+        match self {
+            Self::UngueltigeAnmeldedaten => 1001,
+            Self::SessionUngueltig => 1002,
+            Self::TokenUngueltig => 1003,
+            Self::KontoGesperrt => 1004,
+            Self::Gebannt => 1005,
+            Self::BenutzernameVergeben => 1006,
+            Self::EinladungUngueltig => 1007,
+            Self::BenutzerNichtGefunden => 1008,
+            Self::RegistrierungDeaktiviert => 1009,
+            Self::ZugriffVerweigert => 2001,
+            Self::ScopeFehlt => 2002,
+            Self::KanalNichtGefunden => 3001,
+            Self::KanalVoll => 3002,
+            Self::KanalPasswort => 3003,
+            Self::NachrichtNichtGefunden => 4001,
+            Self::FristAbgelaufen => 4002,
+            Self::DateiNichtGefunden => 5001,
+            Self::DateiZuGross => 5002,
+            Self::KontingentErschoepft => 5003,
+            Self::Speicher => 5004,
+            Self::Codec => 6001,
+            Self::Krypto => 6002,
+            Self::Audio => 6003,
+            Self::Konflikt => 7001,
+            Self::Datenbank => 7002,
+            Self::UngueltigeAnfrage => 8001,
+            Self::ProtokollVersion => 8002,
+            Self::NichtGefunden => 8003,
+            Self::RateLimit => 8004,
+            Self::Zeitlimit => 8005,
+            Self::ServerVoll => 8006,
+            Self::Verbindung => 8007,
+            Self::Intern => 9000,
+            Self::Konfiguration => 9001,
+            Self::Plugin => 9002,
+        }
+    }
+
+    /// Stabiler Name, z.B. `"channel.full"`
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::UngueltigeAnmeldedaten => "auth.invalid_credentials",
+            Self::SessionUngueltig => "auth.session_invalid",
+            Self::TokenUngueltig => "auth.token_invalid",
+            Self::KontoGesperrt => "auth.account_locked",
+            Self::Gebannt => "auth.banned",
+            Self::BenutzernameVergeben => "auth.username_taken",
+            Self::EinladungUngueltig => "auth.invite_invalid",
+            Self::BenutzerNichtGefunden => "auth.user_not_found",
+            Self::RegistrierungDeaktiviert => "auth.registration_disabled",
+            Self::ZugriffVerweigert => "permission.denied",
+            Self::ScopeFehlt => "permission.scope_missing",
+            Self::KanalNichtGefunden => "channel.not_found",
+            Self::KanalVoll => "channel.full",
+            Self::KanalPasswort => "channel.password_required",
+            Self::NachrichtNichtGefunden => "chat.message_not_found",
+            Self::FristAbgelaufen => "chat.edit_window_expired",
+            Self::DateiNichtGefunden => "file.not_found",
+            Self::DateiZuGross => "file.too_large",
+            Self::KontingentErschoepft => "file.quota_exceeded",
+            Self::Speicher => "file.storage_failed",
+            Self::Codec => "voice.codec_unavailable",
+            Self::Krypto => "voice.crypto_failed",
+            Self::Audio => "voice.audio_failed",
+            Self::Konflikt => "db.conflict",
+            Self::Datenbank => "db.failed",
+            Self::UngueltigeAnfrage => "request.invalid",
+            Self::ProtokollVersion => "request.unsupported_version",
+            Self::NichtGefunden => "request.not_found",
+            Self::RateLimit => "request.rate_limited",
+            Self::Zeitlimit => "request.timeout",
+            Self::ServerVoll => "request.server_full",
+            Self::Verbindung => "request.connection_failed",
+            Self::Intern => "internal",
+            Self::Konfiguration => "internal.config",
+            Self::Plugin => "internal.plugin",
+        }
+    }
+
+    /// Sucht einen Code anhand seines Namens
+    pub fn aus_name(name: &str) -> Option<Self> {
+        Self::ALLE.iter().copied().find(|c| c.name() == name)
+    }
+
+    /// Fachbereich des Codes
+    pub fn bereich(self) -> FehlerBereich {
+        match self.nummer() / 1000 {
+            1 => FehlerBereich::Auth,
+            2 => FehlerBereich::Berechtigung,
+            3 => FehlerBereich::Kanal,
+            4 => FehlerBereich::Chat,
+            5 => FehlerBereich::Datei,
+            6 => FehlerBereich::Voice,
+            7 => FehlerBereich::Datenbank,
+            8 => FehlerBereich::Anfrage,
+            _ => FehlerBereich::Intern,
+        }
+    }
+
+    /// Gibt true zurueck wenn eine Wiederholung sinnvoll sein koennte
+    pub fn ist_wiederholbar(self) -> bool {
+        matches!(self, Self::Verbindung | Self::Zeitlimit | Self::RateLimit)
+    }
+
+    /// HTTP-Statuscode fuer REST-Antworten
+    pub fn http_status(self) -> u16 {
+        match self {
+            Self::UngueltigeAnmeldedaten | Self::SessionUngueltig | Self::TokenUngueltig => 401,
+            Self::KontoGesperrt
+            | Self::Gebannt
+            | Self::ZugriffVerweigert
+            | Self::ScopeFehlt
+            | Self::KanalPasswort
+            | Self::FristAbgelaufen
+            | Self::RegistrierungDeaktiviert => 403,
+            Self::BenutzerNichtGefunden
+            | Self::KanalNichtGefunden
+            | Self::NachrichtNichtGefunden
+            | Self::DateiNichtGefunden
+            | Self::NichtGefunden => 404,
+            Self::BenutzernameVergeben | Self::Konflikt | Self::KanalVoll => 409,
+            Self::EinladungUngueltig | Self::UngueltigeAnfrage => 400,
+            Self::DateiZuGross => 413,
+            Self::KontingentErschoepft => 507,
+            Self::ProtokollVersion => 426,
+            Self::RateLimit => 429,
+            Self::Zeitlimit => 504,
+            Self::ServerVoll | Self::Verbindung | Self::Datenbank => 503,
+            Self::Speicher
+            | Self::Codec
+            | Self::Krypto
+            | Self::Audio
+            | Self::Intern
+            | Self::Konfiguration
+            | Self::Plugin => 500,
+        }
+    }
+
+    /// gRPC-Statuscode (numerischer Wert nach gRPC-Spezifikation)
+    pub fn grpc_status(self) -> i32 {
+        // 3 = INVALID_ARGUMENT, 4 = DEADLINE_EXCEEDED, 5 = NOT_FOUND,
+        // 6 = ALREADY_EXISTS, 7 = PERMISSION_DENIED, 8 = RESOURCE_EXHAUSTED,
+        // 9 = FAILED_PRECONDITION, 12 = UNIMPLEMENTED, 13 = INTERNAL,
+        // 14 = UNAVAILABLE, 16 = UNAUTHENTICATED
+        match self {
+            Self::UngueltigeAnmeldedaten | Self::SessionUngueltig | Self::TokenUngueltig => 16,
+            Self::KontoGesperrt
+            | Self::Gebannt
+            | Self::ZugriffVerweigert
+            | Self::ScopeFehlt
+            | Self::KanalPasswort
+            | Self::RegistrierungDeaktiviert => 7,
+            Self::BenutzerNichtGefunden
+            | Self::KanalNichtGefunden
+            | Self::NachrichtNichtGefunden
+            | Self::DateiNichtGefunden
+            | Self::NichtGefunden => 5,
+            Self::BenutzernameVergeben | Self::Konflikt => 6,
+            Self::EinladungUngueltig | Self::UngueltigeAnfrage => 3,
+            Self::KanalVoll
+            | Self::DateiZuGross
+            | Self::KontingentErschoepft
+            | Self::RateLimit
+            | Self::ServerVoll => 8,
+            Self::ProtokollVersion => 12,
+            Self::Zeitlimit => 4,
+            Self::Verbindung | Self::Datenbank => 14,
+            Self::Codec | Self::FristAbgelaufen => 9,
+            Self::Speicher
+            | Self::Krypto
+            | Self::Audio
+            | Self::Intern
+            | Self::Konfiguration
+            | Self::Plugin => 13,
+        }
+    }
+}
+
+impl std::fmt::Display for FehlerCode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
 /// Alle moeglichen Fehler im Speakeasy-System
 #[derive(Debug, Error)]
 pub enum SpeakeasyError {
@@ -21,42 +382,106 @@ pub enum SpeakeasyError {
     #[error("Zeitlimit ueberschritten: {0}")]
     Zeitlimit(String),
 
+    #[error("Rate Limit ueberschritten: bitte warte {retry_after_secs} Sekunden")]
+    RateLimit { retry_after_secs: u64 },
+
     // --- Authentifizierung & Autorisierung ---
     #[error("Authentifizierung fehlgeschlagen: {0}")]
     Authentifizierung(String),
 
+    #[error("Benutzer gesperrt")]
+    KontoGesperrt,
+
+    #[error("Gebannt: {0}")]
+    Gebannt(String),
+
+    #[error("Token ungueltig: {0}")]
+    TokenUngueltig(String),
+
+    #[error("Benutzername bereits vergeben: {0}")]
+    BenutzernameVergeben(String),
+
+    #[error("Einladung ungueltig: {0}")]
+    EinladungUngueltig(String),
+
+    #[error("Registrierung deaktiviert: {0}")]
+    RegistrierungDeaktiviert(String),
+
     #[error("Zugriff verweigert: {0}")]
     ZugriffVerweigert(String),
 
+    #[error("Scope fehlt: {0}")]
+    ScopeFehlt(String),
+
     #[error("Session abgelaufen")]
     SessionAbgelaufen,
 
-    // --- Protokoll ---
+    // --- Protokoll & Eingaben ---
     #[error("Ungueltige Nachricht: {0}")]
     UngueltigeNachricht(String),
 
+    #[error("Ungueltige Eingabe: {0}")]
+    UngueltigeEingabe(String),
+
     #[error("Protokollversion nicht unterstuetzt: erwartet={erwartet}, erhalten={erhalten}")]
     ProtokollVersion { erwartet: u16, erhalten: u16 },
 
     // --- Ressourcen ---
+    #[error("Nicht gefunden: {0}")]
+    NichtGefunden(String),
+
     #[error("Kanal nicht gefunden: {0}")]
     KanalNichtGefunden(String),
 
+    #[error("Kanal ist voll")]
+    KanalVoll,
+
+    #[error("Kanal-Passwort fehlt oder falsch")]
+    KanalPasswort,
+
     #[error("Benutzer nicht gefunden: {0}")]
     BenutzerNichtGefunden(String),
 
     #[error("Server voll: maximale Clientanzahl erreicht")]
     ServerVoll,
 
+    // --- Chat & Dateien ---
+    #[error("Nachricht nicht gefunden: {0}")]
+    NachrichtNichtGefunden(String),
+
+    #[error("Frist fuer Aenderungen abgelaufen ({frist_sek} Sekunden nach dem Senden)")]
+    FristAbgelaufen { frist_sek: u64 },
+
+    #[error("Datei nicht gefunden: {0}")]
+    DateiNichtGefunden(String),
+
+    #[error("Datei zu gross: {groesse} Bytes (Maximum: {max} Bytes)")]
+    DateiZuGross { groesse: i64, max: i64 },
+
+    #[error("Speicherkontingent erschoepft: {belegt} von {max} Bytes belegt")]
+    KontingentErschoepft { belegt: i64, max: i64 },
+
+    #[error("Speicher-Fehler: {0}")]
+    Speicher(String),
+
     // --- Konfiguration ---
     #[error("Konfigurationsfehler: {0}")]
     Konfiguration(String),
 
     // --- Datenbank ---
+    #[error("Eindeutigkeitsverletzung: {0}")]
+    Konflikt(String),
+
     #[error("Datenbankfehler: {0}")]
     Datenbank(String),
 
-    // --- Audio ---
+    // --- Audio & Voice ---
+    #[error("Codec nicht verfuegbar: {0}")]
+    Codec(String),
+
+    #[error("Kryptografiefehler: {0}")]
+    Krypto(String),
+
     #[error("Audiofehler: {0}")]
     Audio(String),
 
@@ -64,6 +489,11 @@ pub enum SpeakeasyError {
     #[error("Plugin-Fehler ({name}): {grund}")]
     Plugin { name: String, grund: String },
 
+    // --- Gegenstelle ---
+    /// Fehlerantwort der Gegenstelle mit bereits zugeordnetem Code
+    #[error("{meldung}")]
+    Server { code: FehlerCode, meldung: String },
+
     // --- Intern ---
     #[error("Interner Fehler: {0}")]
     Intern(String),
@@ -78,12 +508,95 @@ impl SpeakeasyError {
         Self::Intern(msg.into())
     }
 
+    /// Stabiler Fehlercode dieser Variante
+    pub fn code(&self) -> FehlerCode {
+        match self {
+            Self::Verbindung(_) | Self::Getrennt(_) => FehlerCode::Verbindung,
+            Self::Zeitlimit(_) => FehlerCode::Zeitlimit,
+            Self::RateLimit { .. } => FehlerCode::RateLimit,
+            Self::Authentifizierung(_) => FehlerCode::UngueltigeAnmeldedaten,
+            Self::KontoGesperrt => FehlerCode::KontoGesperrt,
+            Self::Gebannt(_) => FehlerCode::Gebannt,
+            Self::TokenUngueltig(_) => FehlerCode::TokenUngueltig,
+            Self::BenutzernameVergeben(_) => FehlerCode::BenutzernameVergeben,
+            Self::EinladungUngueltig(_) => FehlerCode::EinladungUngueltig,
+            Self::RegistrierungDeaktiviert(_) => FehlerCode::RegistrierungDeaktiviert,
+            Self::ZugriffVerweigert(_) => FehlerCode::ZugriffVerweigert,
+            Self::ScopeFehlt(_) => FehlerCode::ScopeFehlt,
+            Self::SessionAbgelaufen => FehlerCode::SessionUngueltig,
+            Self::UngueltigeNachricht(_) | Self::UngueltigeEingabe(_) => {
+                FehlerCode::UngueltigeAnfrage
+            }
+            Self::ProtokollVersion { .. } => FehlerCode::ProtokollVersion,
+            Self::NichtGefunden(_) => FehlerCode::NichtGefunden,
+            Self::KanalNichtGefunden(_) => FehlerCode::KanalNichtGefunden,
+            Self::KanalVoll => FehlerCode::KanalVoll,
+            Self::KanalPasswort => FehlerCode::KanalPasswort,
+            Self::BenutzerNichtGefunden(_) => FehlerCode::BenutzerNichtGefunden,
+            Self::ServerVoll => FehlerCode::ServerVoll,
+            Self::NachrichtNichtGefunden(_) => FehlerCode::NachrichtNichtGefunden,
+            Self::FristAbgelaufen { .. } => FehlerCode::FristAbgelaufen,
+            Self::DateiNichtGefunden(_) => FehlerCode::DateiNichtGefunden,
+            Self::DateiZuGross { .. } => FehlerCode::DateiZuGross,
+            Self::KontingentErschoepft { .. } => FehlerCode::KontingentErschoepft,
+            Self::Speicher(_) => FehlerCode::Speicher,
+            Self::Konfiguration(_) => FehlerCode::Konfiguration,
+            Self::Konflikt(_) => FehlerCode::Konflikt,
+            Self::Datenbank(_) => FehlerCode::Datenbank,
+            Self::Codec(_) => FehlerCode::Codec,
+            Self::Krypto(_) => FehlerCode::Krypto,
+            Self::Audio(_) => FehlerCode::Audio,
+            Self::Plugin { .. } => FehlerCode::Plugin,
+            Self::Server { code, .. } => *code,
+            Self::Intern(_) | Self::Anyhow(_) => FehlerCode::Intern,
+        }
+    }
+
     /// Gibt true zurueck wenn der Fehler wiederholbar sein koennte
     pub fn ist_wiederholbar(&self) -> bool {
-        matches!(
-            self,
-            Self::Zeitlimit(_) | Self::Verbindung(_) | Self::Getrennt(_)
-        )
+        self.code().ist_wiederholbar()
+    }
+
+    /// Maschinenlesbare Details fuer Fehlerantworten
+    ///
+    /// `{"code": "channel.full", "nummer": 3002}`; Rate-Limits zusaetzlich
+    /// mit `retry_after_secs`, abgelaufene Bearbeitungsfristen mit
+    /// `window_secs`.
+    pub fn details(&self) -> serde_json::Value {
+        let code = self.code();
+        let mut details = serde_json::json!({
+            "code": code.name(),
+            "nummer": code.nummer(),
+        });
+        match self {
+            Self::RateLimit { retry_after_secs } => {
+                details["retry_after_secs"] = (*retry_after_secs).into();
+            }
+            Self::FristAbgelaufen { frist_sek } => {
+                details["window_secs"] = (*frist_sek).into();
+            }
+            _ => {}
+        }
+        details
+    }
+}
+
+/// Kein eigener Fehler-Enum: IO-Fehler sind Verbindungs- bzw. interne Fehler
+impl From<std::io::Error> for SpeakeasyError {
+    fn from(e: std::io::Error) -> Self {
+        use std::io::ErrorKind;
+        match e.kind() {
+            ErrorKind::TimedOut => Self::Zeitlimit(e.to_string()),
+            ErrorKind::NotFound => Self::NichtGefunden(e.to_string()),
+            ErrorKind::PermissionDenied => Self::ZugriffVerweigert(e.to_string()),
+            ErrorKind::ConnectionRefused
+            | ErrorKind::ConnectionReset
+            | ErrorKind::ConnectionAborted
+            | ErrorKind::NotConnected
+            | ErrorKind::BrokenPipe
+            | ErrorKind::UnexpectedEof => Self::Verbindung(e.to_string()),
+            _ => Self::Intern(e.to_string()),
+        }
     }
 }
 
@@ -106,6 +619,52 @@ mod tests {
         assert!(!SpeakeasyError::ZugriffVerweigert("test".into()).ist_wiederholbar());
     }
 
+    #[test]
+    fn codes_sind_eindeutig_und_stabil() {
+        let mut nummern = std::collections::HashSet::new();
+        let mut namen = std::collections::HashSet::new();
+        for code in FehlerCode::ALLE {
+            assert!(nummern.insert(code.nummer()), "Nummer doppelt: {code}");
+            assert!(namen.insert(code.name()), "Name doppelt: {code}");
+            assert!(code.name().starts_with(code.bereich().name()));
+            assert_eq!(FehlerCode::aus_name(code.name()), Some(*code));
+            // Serde-Name und name() muessen uebereinstimmen
+            assert_eq!(
+                serde_json::to_value(code).unwrap(),
+                serde_json::json!(code.name())
+            );
+        }
+        assert_eq!(FehlerCode::KanalVoll.nummer(), 3002);
+        assert_eq!(FehlerCode::KanalVoll.name(), "channel.full");
+        assert_eq!(FehlerCode::aus_name("gibt.es.nicht"), None);
+    }
+
+    #[test]
+    fn details_enthalten_code() {
+        let e = SpeakeasyError::RateLimit {
+            retry_after_secs: 7,
+        };
+        assert_eq!(
+            e.details(),
+            serde_json::json!({"code": "request.rate_limited", "nummer": 8004, "retry_after_secs": 7})
+        );
+        assert_eq!(SpeakeasyError::KanalVoll.code().http_status(), 409);
+        let e = SpeakeasyError::FristAbgelaufen { frist_sek: 900 };
+        assert_eq!(
+            e.details(),
+            serde_json::json!({"code": "chat.edit_window_expired", "nummer": 4002, "window_secs": 900})
+        );
+        assert_eq!(SpeakeasyError::SessionAbgelaufen.code().grpc_status(), 16);
+    }
+
+    #[test]
+    fn io_fehler_zuordnung() {
+        let e = std::io::Error::new(std::io::ErrorKind::ConnectionReset, "weg");
+        assert_eq!(SpeakeasyError::from(e).code(), FehlerCode::Verbindung);
+        let e = std::io::Error::other("kaputt");
+        assert_eq!(SpeakeasyError::from(e).code(), FehlerCode::Intern);
+    }
+
     #[test]
     fn protokoll_version_fehler() {
         let e = SpeakeasyError::ProtokollVersion {
@@ -8,5 +8,5 @@ pub mod event;
 pub mod types;
 
 // Re-Exporte fuer bequemen Zugriff
-pub use error::{Result, SpeakeasyError};
+pub use error::{FehlerBereich, FehlerCode, Result, SpeakeasyError};
 pub use types::{ChannelId, ServerId, UserId};
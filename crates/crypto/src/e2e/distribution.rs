@@ -0,0 +1,183 @@
+//! Schluessel-Verteilung ueber den Control-Kanal
+//!
+//! Bruecke zwischen den Gruppen-Schluesseln und den Protokoll-Nachrichten:
+//! Der Verteiler eines Channels verpackt nach einem
+//! `E2EKeyRotationRequired` den neuen Schluessel je Empfaenger mit
+//! [`wrap_key_for_recipient`] in eine `GroupKeyDistribute`; jeder Empfaenger
+//! packt seinen Eintrag mit dem eigenen privaten X25519-Schluessel aus.
+//!
+//! Eintraege in `encrypted_keys` sind nach der UUID des Empfaengers benannt.
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use rand::rngs::OsRng;
+use rand::RngCore;
+use speakeasy_core::types::UserId;
+use speakeasy_protocol::control::E2EMemberKey;
+use speakeasy_protocol::crypto::{AeadAlgorithm, E2EKeyMessage, KeyPurpose};
+use x25519_dalek::{PublicKey as X25519PublicKey, StaticSecret};
+
+use crate::e2e::group_key::{unwrap_key_for_recipient, wrap_key_for_recipient};
+use crate::error::{CryptoError, CryptoResult};
+use crate::types::{GroupKey, GroupKeyAlgorithm};
+
+/// Erzeugt das X25519-Schluesselpaar eines Empfaengers
+///
+/// Gibt den privaten Schluessel und den oeffentlichen als Base64 zurueck
+/// (so wie er im `VoiceInit` gemeldet wird).
+pub fn generate_member_keypair() -> ([u8; 32], String) {
+    let mut privat = [0u8; 32];
+    OsRng.fill_bytes(&mut privat);
+    let public = X25519PublicKey::from(&StaticSecret::from(privat));
+    (privat, STANDARD.encode(public.as_bytes()))
+}
+
+/// Dekodiert einen oeffentlichen X25519-Schluessel (Base64, 32 Bytes)
+pub fn decode_public_key(base64: &str) -> CryptoResult<[u8; 32]> {
+    let bytes = STANDARD.decode(base64)?;
+    let laenge = bytes.len();
+    bytes
+        .try_into()
+        .map_err(|_| CryptoError::UngueltigeSchluesselLaenge {
+            erwartet: 32,
+            erhalten: laenge,
+        })
+}
+
+/// Verpackt einen Gruppen-Schluessel fuer alle Empfaenger
+///
+/// `valid_from_ms` ist der Zeitpunkt, ab dem der Verteiler mit dem neuen
+/// Schluessel sendet.
+pub fn build_key_distribution(
+    key: &GroupKey,
+    members: &[E2EMemberKey],
+    valid_from_ms: u64,
+) -> CryptoResult<E2EKeyMessage> {
+    let mut encrypted_keys = std::collections::HashMap::with_capacity(members.len());
+    for member in members {
+        let public_key = decode_public_key(&member.public_key)?;
+        let wrapped = wrap_key_for_recipient(key, &public_key)?;
+        encrypted_keys.insert(member.user_id.inner().to_string(), STANDARD.encode(wrapped));
+    }
+
+    Ok(E2EKeyMessage::GroupKeyDistribute {
+        key_id: key.key_id,
+        epoch: key.epoch,
+        key_algorithm: match key.algorithm {
+            GroupKeyAlgorithm::Aes256Gcm => AeadAlgorithm::Aes256Gcm,
+            GroupKeyAlgorithm::ChaCha20Poly1305 => AeadAlgorithm::ChaCha20Poly1305,
+        },
+        purpose: KeyPurpose::Audio,
+        encrypted_keys,
+        // wrap_key_for_recipient nutzt immer AES-256-GCM
+        wrapping_algorithm: AeadAlgorithm::Aes256Gcm,
+        valid_from_ms,
+        expires_at_ms: 0,
+    })
+}
+
+/// Packt den eigenen Eintrag einer `GroupKeyDistribute` aus
+pub fn accept_key_distribution(
+    message: &E2EKeyMessage,
+    user_id: &UserId,
+    private_key: &[u8; 32],
+    channel_id: &str,
+) -> CryptoResult<GroupKey> {
+    let E2EKeyMessage::GroupKeyDistribute {
+        key_id,
+        epoch,
+        key_algorithm,
+        encrypted_keys,
+        ..
+    } = message
+    else {
+        return Err(CryptoError::UngueltigeDaten(
+            "Keine Schluessel-Verteilung".to_string(),
+        ));
+    };
+
+    let algorithm = match key_algorithm {
+        AeadAlgorithm::Aes256Gcm => GroupKeyAlgorithm::Aes256Gcm,
+        AeadAlgorithm::ChaCha20Poly1305 => GroupKeyAlgorithm::ChaCha20Poly1305,
+        AeadAlgorithm::Aes128Gcm => {
+            return Err(CryptoError::UngueltigeDaten(
+                "AES-128-GCM wird fuer Gruppen-Schluessel nicht unterstuetzt".to_string(),
+            ))
+        }
+    };
+    let eintrag = encrypted_keys
+        .get(&user_id.inner().to_string())
+        .ok_or_else(|| CryptoError::KeinSchluessel {
+            channel_id: channel_id.to_string(),
+            epoch: *epoch,
+        })?;
+
+    unwrap_key_for_recipient(
+        &STANDARD.decode(eintrag)?,
+        private_key,
+        channel_id,
+        *key_id,
+        *epoch,
+        algorithm,
+    )
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::e2e::group_key::create_group_key;
+
+    fn mitglied() -> (E2EMemberKey, [u8; 32]) {
+        let (privat, public_key) = generate_member_keypair();
+        (
+            E2EMemberKey {
+                user_id: UserId::new(),
+                public_key,
+            },
+            privat,
+        )
+    }
+
+    #[test]
+    fn verteilung_roundtrip_fuer_alle_empfaenger() {
+        let key = create_group_key("ch", 7, 3, GroupKeyAlgorithm::ChaCha20Poly1305).unwrap();
+        let (a, a_priv) = mitglied();
+        let (b, b_priv) = mitglied();
+
+        let nachricht = build_key_distribution(&key, &[a.clone(), b.clone()], 1000).unwrap();
+
+        for (m, privat) in [(a, a_priv), (b, b_priv)] {
+            let erhalten = accept_key_distribution(&nachricht, &m.user_id, &privat, "ch").unwrap();
+            assert_eq!(erhalten.epoch, 3);
+            assert_eq!(erhalten.key_id, 7);
+            assert_eq!(erhalten.algorithm, GroupKeyAlgorithm::ChaCha20Poly1305);
+            assert_eq!(erhalten.key_bytes.as_bytes(), key.key_bytes.as_bytes());
+        }
+    }
+
+    #[test]
+    fn nicht_bedachter_empfaenger_erhaelt_keinen_schluessel() {
+        let key = create_group_key("ch", 1, 0, GroupKeyAlgorithm::Aes256Gcm).unwrap();
+        let (a, _) = mitglied();
+        let (fremd, fremd_priv) = mitglied();
+
+        let nachricht = build_key_distribution(&key, &[a], 0).unwrap();
+        assert!(matches!(
+            accept_key_distribution(&nachricht, &fremd.user_id, &fremd_priv, "ch"),
+            Err(CryptoError::KeinSchluessel { epoch: 0, .. })
+        ));
+    }
+
+    #[test]
+    fn ungueltiger_oeffentlicher_schluessel() {
+        assert!(decode_public_key("kein base64!").is_err());
+        assert!(matches!(
+            decode_public_key(&STANDARD.encode([0u8; 16])),
+            Err(CryptoError::UngueltigeSchluesselLaenge { erhalten: 16, .. })
+        ));
+    }
+}
@@ -0,0 +1,221 @@
+//! Empfangsseitiger Schluesselring pro Channel
+//!
+//! Nach einer Rotation sind noch Pakete der alten Epoch unterwegs (Jitter-
+//! Puffer, Netzwerk-Umordnung, Sender mit spaet installiertem Schluessel).
+//! Der Ring haelt deshalb neben dem aktuellen Schluessel den vorherigen fuer
+//! ein kurzes Ueberlappungsfenster. Welcher Schluessel passt, steht in der
+//! Nonce jedes Pakets (`[epoch(4)] [sequence(4)] [random(4)]`).
+
+use std::time::{Duration, Instant};
+
+use crate::e2e::decrypt::decrypt_audio;
+use crate::error::{CryptoError, CryptoResult};
+use crate::types::{EncryptedPayload, GroupKey};
+
+/// Standard-Ueberlappung: deckt Jitter-Puffer und Verteilungslaufzeit ab
+pub const STANDARD_UEBERLAPPUNG: Duration = Duration::from_secs(2);
+
+/// Aktueller und (befristet) vorheriger Gruppen-Schluessel eines Channels
+#[derive(Debug)]
+pub struct EpochKeyRing {
+    channel_id: String,
+    ueberlappung: Duration,
+    aktuell: Option<GroupKey>,
+    /// Vorheriger Schluessel mit Ablaufzeitpunkt
+    vorherig: Option<(GroupKey, Instant)>,
+}
+
+impl EpochKeyRing {
+    pub fn new(channel_id: &str, ueberlappung: Duration) -> Self {
+        Self {
+            channel_id: channel_id.to_string(),
+            ueberlappung,
+            aktuell: None,
+            vorherig: None,
+        }
+    }
+
+    /// Installiert einen neuen Schluessel
+    ///
+    /// Der bisherige bleibt bis `jetzt + ueberlappung` fuer Pakete seiner
+    /// Epoch gueltig. Schluessel fuer eine aeltere oder die gleiche Epoch
+    /// (verspaetete Verteilung) werden abgelehnt.
+    pub fn install(&mut self, key: GroupKey, jetzt: Instant) -> CryptoResult<()> {
+        if key.channel_id != self.channel_id {
+            return Err(CryptoError::UngueltigeDaten(format!(
+                "Schluessel fuer Kanal {} statt {}",
+                key.channel_id, self.channel_id
+            )));
+        }
+        if let Some(aktuell) = &self.aktuell {
+            if key.epoch <= aktuell.epoch {
+                return Err(CryptoError::EpochMismatch {
+                    erwartet: aktuell.epoch + 1,
+                    erhalten: key.epoch,
+                });
+            }
+        }
+        self.vorherig = self
+            .aktuell
+            .replace(key)
+            .map(|alt| (alt, jetzt + self.ueberlappung));
+        Ok(())
+    }
+
+    /// Schluessel zum Verschluesseln eigener Pakete
+    pub fn current(&self) -> Option<&GroupKey> {
+        self.aktuell.as_ref()
+    }
+
+    /// Aktuelle Epoch (None = noch kein Schluessel erhalten)
+    pub fn current_epoch(&self) -> Option<u32> {
+        self.aktuell.as_ref().map(|k| k.epoch)
+    }
+
+    /// Sucht den Schluessel fuer eine Epoch
+    ///
+    /// Der vorherige Schluessel gilt nur bis zum Ende der Ueberlappung.
+    pub fn key_for_epoch(&self, epoch: u32, jetzt: Instant) -> CryptoResult<&GroupKey> {
+        if let Some(key) = self.aktuell.as_ref().filter(|k| k.epoch == epoch) {
+            return Ok(key);
+        }
+        match &self.vorherig {
+            Some((key, bis)) if key.epoch == epoch => {
+                if jetzt < *bis {
+                    Ok(key)
+                } else {
+                    Err(CryptoError::SchluesselWiderrufen { epoch })
+                }
+            }
+            _ => Err(CryptoError::KeinSchluessel {
+                channel_id: self.channel_id.clone(),
+                epoch,
+            }),
+        }
+    }
+
+    /// Entschluesselt ein Paket mit dem Schluessel seiner Epoch
+    pub fn decrypt(&self, payload: &EncryptedPayload, jetzt: Instant) -> CryptoResult<Vec<u8>> {
+        let key = self.key_for_epoch(payload.nonce.epoch(), jetzt)?;
+        decrypt_audio(payload, key)
+    }
+
+    /// Entschluesselt rohe Bytes aus dem Netzwerk
+    pub fn decrypt_bytes(&self, data: &[u8], jetzt: Instant) -> CryptoResult<Vec<u8>> {
+        let payload = EncryptedPayload::from_bytes(data).ok_or_else(|| {
+            CryptoError::UngueltigeDaten("Ungueltige Payload-Struktur".to_string())
+        })?;
+        self.decrypt(&payload, jetzt)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::e2e::encrypt::encrypt_audio;
+    use crate::e2e::group_key::{create_group_key, rotate_group_key};
+    use crate::types::GroupKeyAlgorithm;
+
+    const FENSTER: Duration = Duration::from_millis(500);
+
+    fn ring_mit_epoch0(jetzt: Instant) -> (EpochKeyRing, GroupKey) {
+        let key = create_group_key("ch", 1, 0, GroupKeyAlgorithm::Aes256Gcm).unwrap();
+        let mut ring = EpochKeyRing::new("ch", FENSTER);
+        ring.install(key.clone(), jetzt).unwrap();
+        (ring, key)
+    }
+
+    #[test]
+    fn paket_der_alten_epoch_nach_rotation_entschluesselbar() {
+        let t0 = Instant::now();
+        let (mut ring, alt) = ring_mit_epoch0(t0);
+        let neu = rotate_group_key(&alt).unwrap();
+
+        // Paket aus Epoch 0 ist noch unterwegs, waehrend Epoch 1 installiert wird
+        let verspaetet = encrypt_audio(b"alte epoch", &alt, 7, 41).unwrap();
+        ring.install(neu.clone(), t0).unwrap();
+        assert_eq!(ring.current_epoch(), Some(1));
+
+        let klartext = ring
+            .decrypt_bytes(&verspaetet.to_bytes(), t0 + FENSTER / 2)
+            .unwrap();
+        assert_eq!(klartext, b"alte epoch");
+
+        // Pakete der neuen Epoch gehen gleichzeitig
+        let aktuell = encrypt_audio(b"neue epoch", &neu, 7, 42).unwrap();
+        assert_eq!(
+            ring.decrypt(&aktuell, t0 + FENSTER / 2).unwrap(),
+            b"neue epoch"
+        );
+    }
+
+    #[test]
+    fn alte_epoch_nach_ablauf_der_ueberlappung_abgelehnt() {
+        let t0 = Instant::now();
+        let (mut ring, alt) = ring_mit_epoch0(t0);
+        ring.install(rotate_group_key(&alt).unwrap(), t0).unwrap();
+
+        let verspaetet = encrypt_audio(b"zu spaet", &alt, 7, 41).unwrap();
+        let ergebnis = ring.decrypt(&verspaetet, t0 + FENSTER);
+        assert!(matches!(
+            ergebnis,
+            Err(CryptoError::SchluesselWiderrufen { epoch: 0 })
+        ));
+    }
+
+    #[test]
+    fn nur_die_direkt_vorherige_epoch_ueberlappt() {
+        let t0 = Instant::now();
+        let (mut ring, e0) = ring_mit_epoch0(t0);
+        let e1 = rotate_group_key(&e0).unwrap();
+        let e2 = rotate_group_key(&e1).unwrap();
+        ring.install(e1.clone(), t0).unwrap();
+        ring.install(e2, t0).unwrap();
+
+        let paket_e0 = encrypt_audio(b"e0", &e0, 1, 1).unwrap();
+        assert!(matches!(
+            ring.decrypt(&paket_e0, t0),
+            Err(CryptoError::KeinSchluessel { epoch: 0, .. })
+        ));
+        let paket_e1 = encrypt_audio(b"e1", &e1, 1, 2).unwrap();
+        assert_eq!(ring.decrypt(&paket_e1, t0).unwrap(), b"e1");
+    }
+
+    #[test]
+    fn zukuenftige_epoch_ohne_schluessel() {
+        let t0 = Instant::now();
+        let (ring, alt) = ring_mit_epoch0(t0);
+        let spaeter = rotate_group_key(&alt).unwrap();
+        let paket = encrypt_audio(b"vorausgeeilt", &spaeter, 1, 1).unwrap();
+        assert!(matches!(
+            ring.decrypt(&paket, t0),
+            Err(CryptoError::KeinSchluessel { epoch: 1, .. })
+        ));
+    }
+
+    #[test]
+    fn veraltete_verteilung_wird_nicht_installiert() {
+        let t0 = Instant::now();
+        let (mut ring, alt) = ring_mit_epoch0(t0);
+        let neu = rotate_group_key(&alt).unwrap();
+        ring.install(neu, t0).unwrap();
+
+        assert!(matches!(
+            ring.install(alt, t0),
+            Err(CryptoError::EpochMismatch { erhalten: 0, .. })
+        ));
+        assert_eq!(ring.current_epoch(), Some(1));
+    }
+
+    #[test]
+    fn fremder_kanal_wird_abgelehnt() {
+        let mut ring = EpochKeyRing::new("ch", FENSTER);
+        let key = create_group_key("anderer", 1, 0, GroupKeyAlgorithm::Aes256Gcm).unwrap();
+        assert!(ring.install(key, Instant::now()).is_err());
+        assert!(ring.current().is_none());
+    }
+}
@@ -8,18 +8,26 @@
 //! 2. Bei Channel-Beitritt: X25519 Key Exchange mit dem Key-Verteiler
 //! 3. Gruppen-Schluessel wird sicher an alle Mitglieder verteilt
 //! 4. Audio wird mit dem Gruppen-Schluessel (AES-256-GCM) verschluesselt
-//! 5. Bei Join/Leave: Key Rotation (neue Epoch)
+//! 5. Bei Join/Leave: Key Rotation (neue Epoch), verteilt vom Key-Verteiler
+//!    (`distribution`); Empfaenger behalten den alten Schluessel kurz im
+//!    `EpochKeyRing`, bis die Pakete der alten Epoch durch sind
 
 pub mod decrypt;
+pub mod distribution;
 pub mod encrypt;
 pub mod group_key;
 pub mod key_exchange;
 pub mod key_manager;
+pub mod key_ring;
 
 pub use decrypt::{decrypt_audio, decrypt_audio_bytes};
+pub use distribution::{
+    accept_key_distribution, build_key_distribution, decode_public_key, generate_member_keypair,
+};
 pub use encrypt::encrypt_audio;
 pub use group_key::{
     create_group_key, rotate_group_key, unwrap_key_for_recipient, wrap_key_for_recipient,
 };
 pub use key_exchange::{hkdf_derive, KeyExchangeClient, KeyExchangeServer, SharedSecret};
 pub use key_manager::GroupKeyManager;
+pub use key_ring::EpochKeyRing;
@@ -1,5 +1,6 @@
 //! Fehlertypen fuer das Kryptografie-Subsystem
 
+use speakeasy_core::SpeakeasyError;
 use thiserror::Error;
 
 /// Fehler im Kryptografie-Subsystem
@@ -61,3 +62,99 @@ pub enum CryptoError {
 }
 
 pub type CryptoResult<T> = Result<T, CryptoError>;
+
+impl From<CryptoError> for SpeakeasyError {
+    fn from(e: CryptoError) -> Self {
+        match e {
+            CryptoError::UngueltigeDaten(grund) => Self::UngueltigeEingabe(grund),
+            CryptoError::Base64(_) => Self::UngueltigeEingabe(e.to_string()),
+            CryptoError::Io(io) => io.into(),
+            CryptoError::Anyhow(e) => Self::Anyhow(e),
+            CryptoError::SchluesselGenerierung(_)
+            | CryptoError::KeyExchange(_)
+            | CryptoError::Verschluesselung(_)
+            | CryptoError::Entschluesselung(_)
+            | CryptoError::Signierung(_)
+            | CryptoError::SignaturVerifikation(_)
+            | CryptoError::UngueltigeNonce { .. }
+            | CryptoError::UngueltigeSchluesselLaenge { .. }
+            | CryptoError::EpochMismatch { .. }
+            | CryptoError::KeinSchluessel { .. }
+            | CryptoError::SchluesselWiderrufen { .. }
+            | CryptoError::ZertifikatGenerierung(_)
+            | CryptoError::Tls(_)
+            | CryptoError::KeyDerivation(_) => Self::Krypto(e.to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use speakeasy_core::FehlerCode;
+
+    /// Ein Beispiel je Variante; der `match` in `From` ist ohne Wildcard und
+    /// bricht den Build, sobald eine Variante hinzukommt
+    fn alle_varianten() -> Vec<CryptoError> {
+        use base64::Engine;
+        vec![
+            CryptoError::SchluesselGenerierung("x".into()),
+            CryptoError::KeyExchange("x".into()),
+            CryptoError::Verschluesselung("x".into()),
+            CryptoError::Entschluesselung("x".into()),
+            CryptoError::Signierung("x".into()),
+            CryptoError::SignaturVerifikation("x".into()),
+            CryptoError::UngueltigeNonce {
+                erwartet: 12,
+                erhalten: 8,
+            },
+            CryptoError::UngueltigeSchluesselLaenge {
+                erwartet: 32,
+                erhalten: 16,
+            },
+            CryptoError::UngueltigeDaten("x".into()),
+            CryptoError::EpochMismatch {
+                erwartet: 2,
+                erhalten: 1,
+            },
+            CryptoError::KeinSchluessel {
+                channel_id: "k".into(),
+                epoch: 1,
+            },
+            CryptoError::SchluesselWiderrufen { epoch: 1 },
+            CryptoError::ZertifikatGenerierung("x".into()),
+            CryptoError::Tls("x".into()),
+            CryptoError::KeyDerivation("x".into()),
+            CryptoError::Base64(
+                base64::engine::general_purpose::STANDARD
+                    .decode("%%%")
+                    .unwrap_err(),
+            ),
+            CryptoError::Io(std::io::Error::new(
+                std::io::ErrorKind::ConnectionReset,
+                "weg",
+            )),
+            CryptoError::Anyhow(anyhow::anyhow!("unerwartet")),
+        ]
+    }
+
+    #[test]
+    fn jede_variante_hat_einen_fachlichen_code() {
+        for e in alle_varianten() {
+            let erlaubt = matches!(e, CryptoError::Anyhow(_));
+            let anzeige = e.to_string();
+            let code = SpeakeasyError::from(e).code();
+            assert!(erlaubt || code != FehlerCode::Intern, "{anzeige}");
+        }
+    }
+
+    #[test]
+    fn krypto_fehler_gehoeren_zum_voice_bereich() {
+        let fehler = SpeakeasyError::from(CryptoError::SchluesselWiderrufen { epoch: 3 });
+        assert_eq!(fehler.code(), FehlerCode::Krypto);
+        assert_eq!(
+            fehler.code().bereich(),
+            speakeasy_core::FehlerBereich::Voice
+        );
+    }
+}
@@ -21,9 +21,10 @@ pub use identity::{Identity, PublicIdentity};
 pub use types::{EncryptedPayload, GroupKey, GroupKeyAlgorithm, Nonce, PublicKey, SecretBytes};
 
 pub use e2e::{
-    create_group_key, decrypt_audio, decrypt_audio_bytes, encrypt_audio, hkdf_derive,
-    rotate_group_key, unwrap_key_for_recipient, wrap_key_for_recipient, GroupKeyManager,
-    KeyExchangeClient, KeyExchangeServer, SharedSecret,
+    accept_key_distribution, build_key_distribution, create_group_key, decode_public_key,
+    decrypt_audio, decrypt_audio_bytes, encrypt_audio, generate_member_keypair, hkdf_derive,
+    rotate_group_key, unwrap_key_for_recipient, wrap_key_for_recipient, EpochKeyRing,
+    GroupKeyManager, KeyExchangeClient, KeyExchangeServer, SharedSecret,
 };
 
 pub use dtls::{
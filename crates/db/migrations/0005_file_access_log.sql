@@ -0,0 +1,16 @@
+-- Speakeasy Migration v5
+-- Zugriffsprotokoll fuer Datei-Downloads (append-only)
+-- user_id ist NULL wenn der Server im Aggregat-Modus protokolliert
+
+CREATE TABLE IF NOT EXISTS file_access_log (
+    id           TEXT PRIMARY KEY NOT NULL,
+    file_id      TEXT NOT NULL REFERENCES files(id) ON DELETE CASCADE,
+    user_id      TEXT REFERENCES users(id) ON DELETE SET NULL,
+    timestamp    TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%SZ', 'now')),
+    bytes_served INTEGER NOT NULL DEFAULT 0,
+    completed    INTEGER NOT NULL DEFAULT 0
+);
+
+CREATE INDEX IF NOT EXISTS idx_file_access_log_file_id ON file_access_log(file_id);
+CREATE INDEX IF NOT EXISTS idx_file_access_log_user_id ON file_access_log(user_id);
+CREATE INDEX IF NOT EXISTS idx_file_access_log_timestamp ON file_access_log(timestamp);
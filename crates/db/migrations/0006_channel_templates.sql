@@ -0,0 +1,45 @@
+-- Speakeasy Migration v6
+-- Kanal-Vorlagen: wiederverwendbare Kanal-Teilbaeume (JSON-Definition)
+-- Zusaetzlich Codec-Profil pro Kanal (Name eines Audio-Presets, NULL = Standard)
+
+ALTER TABLE channels ADD COLUMN codec_profile TEXT;
+
+CREATE TABLE IF NOT EXISTS channel_templates (
+    id              TEXT PRIMARY KEY NOT NULL,
+    name            TEXT UNIQUE NOT NULL,
+    description     TEXT,
+    definition_json TEXT NOT NULL,              -- Wurzelknoten des Teilbaums
+    builtin         INTEGER NOT NULL DEFAULT 0, -- 1 = mitgeliefert, nicht loeschbar
+    created_by      TEXT REFERENCES users(id) ON DELETE SET NULL,
+    created_at      TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%SZ', 'now'))
+);
+
+-- Mitgelieferte Beispiel-Vorlagen
+INSERT OR IGNORE INTO channel_templates (id, name, description, definition_json, builtin)
+VALUES (
+    '00000000-0000-4000-8000-00000000a001',
+    'Team-Kategorie',
+    'Kategorie mit Lobby, zwei Team-Kanaelen und AFK-Kanal',
+    '{"name":"Teams","codec_profile":"speech","children":[
+        {"name":"Lobby"},
+        {"name":"Team 1","max_clients":5},
+        {"name":"Team 2","max_clients":5},
+        {"name":"AFK","codec_profile":"low_bandwidth","permissions":[
+            {"target":{"type":"channel_default"},"permission_key":"b_client_poke","wert":{"type":"TriState","value":"Deny"}}
+        ]}
+    ]}',
+    1
+);
+
+INSERT OR IGNORE INTO channel_templates (id, name, description, definition_json, builtin)
+VALUES (
+    '00000000-0000-4000-8000-00000000a002',
+    'Community-Bereich',
+    'Ankuendigungen (Text), Plauder- und Musik-Kanal',
+    '{"name":"Community","children":[
+        {"name":"Ankuendigungen","channel_type":"text","topic":"Neuigkeiten vom Team"},
+        {"name":"Plaudern","codec_profile":"balanced"},
+        {"name":"Musik","codec_profile":"music","max_clients":10}
+    ]}',
+    1
+);
@@ -0,0 +1,10 @@
+-- Speakeasy Migration v7
+-- Zur Laufzeit aenderbare Server-Einstellungen (Schluessel/Wert)
+-- Werte werden als Text gespeichert; die Typisierung erfolgt beim Lesen.
+-- Beim ersten Start werden fehlende Schluessel aus der Konfigurationsdatei vorbelegt.
+
+CREATE TABLE IF NOT EXISTS server_settings (
+    key         TEXT PRIMARY KEY NOT NULL,
+    value       TEXT NOT NULL,
+    updated_at  TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%SZ', 'now'))
+);
@@ -0,0 +1,23 @@
+-- Speakeasy Migration v8
+-- Geplante Aktionen des Zeitplaners (z.B. Event-Kanaele anlegen/loeschen)
+-- Alle Zeitpunkte in UTC ('YYYY-MM-DDTHH:MM:SSZ', per Textvergleich sortierbar);
+-- time_zone gilt nur fuer die Auswertung des Cron-Ausdrucks
+
+CREATE TABLE IF NOT EXISTS scheduled_actions (
+    id          TEXT PRIMARY KEY NOT NULL,
+    name        TEXT NOT NULL,
+    action_json TEXT NOT NULL,                -- Aktion inkl. Typ und Nutzdaten
+    run_at      TEXT,                         -- einmaliger Zeitpunkt (sonst NULL)
+    cron        TEXT,                         -- Cron-Ausdruck (sonst NULL)
+    time_zone   TEXT NOT NULL DEFAULT 'UTC',  -- IANA-Zeitzone
+    next_run_at TEXT,                         -- NULL = erledigt oder abgebrochen
+    last_run_at TEXT,
+    created_by  TEXT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    scopes_json TEXT,                         -- Token-Scopes; NULL = Session
+    cancelled   INTEGER NOT NULL DEFAULT 0,
+    created_at  TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%SZ', 'now')),
+    CHECK ((run_at IS NULL) <> (cron IS NULL))
+);
+
+CREATE INDEX IF NOT EXISTS idx_scheduled_actions_next_run
+    ON scheduled_actions(next_run_at) WHERE cancelled = 0;
@@ -0,0 +1,26 @@
+-- Speakeasy Migration v9
+-- Datenexport und Loeschung von Benutzerkonten
+-- Alle Zeitpunkte in UTC ('YYYY-MM-DDTHH:MM:SSZ', per Textvergleich sortierbar)
+
+-- Export-Archive: abholbar genau einmal ueber einen Token (gespeichert wird
+-- nur dessen SHA-256); die Zeile begrenzt zugleich die Export-Haeufigkeit
+CREATE TABLE IF NOT EXISTS account_exports (
+    id            TEXT PRIMARY KEY NOT NULL,
+    user_id       TEXT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    token_hash    TEXT UNIQUE NOT NULL,
+    storage_path  TEXT NOT NULL,
+    size_bytes    INTEGER,                  -- NULL solange das Archiv erstellt wird
+    created_at    TEXT NOT NULL,
+    completed_at  TEXT,
+    expires_at    TEXT NOT NULL,
+    downloaded_at TEXT
+);
+
+CREATE INDEX IF NOT EXISTS idx_account_exports_user ON account_exports(user_id, created_at);
+CREATE INDEX IF NOT EXISTS idx_account_exports_expires ON account_exports(expires_at);
+
+-- Platzhalter-Absender fuer Nachrichten geloeschter Konten (inaktiv, ohne
+-- gueltigen Passwort-Hash, erscheint nicht in Benutzerlisten)
+INSERT OR IGNORE INTO users (id, username, password_hash, created_at, is_active, password_changed)
+VALUES ('00000000-0000-0000-0000-000000000000', '[geloescht]', '!',
+        strftime('%Y-%m-%dT%H:%M:%SZ', 'now'), 0, 1);
@@ -0,0 +1,19 @@
+-- Speakeasy Migration v10
+-- Soundboard: kurze Clips, bei der Registrierung einmalig nach Opus kodiert
+-- frames enthaelt die Opus-Frames hintereinander, jeweils mit 2 Byte Laenge
+-- (Big Endian) davor; channel_id NULL = serverweit verfuegbar
+
+CREATE TABLE IF NOT EXISTS soundboard_sounds (
+    id          TEXT PRIMARY KEY NOT NULL,
+    name        TEXT NOT NULL,
+    channel_id  TEXT REFERENCES channels(id) ON DELETE CASCADE,
+    file_id     TEXT,                         -- Quelldatei (darf spaeter fehlen)
+    frame_ms    INTEGER NOT NULL,
+    duration_ms INTEGER NOT NULL,
+    frames      BLOB NOT NULL,
+    created_by  TEXT REFERENCES users(id) ON DELETE SET NULL,
+    created_at  TEXT NOT NULL
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_soundboard_sounds_name
+    ON soundboard_sounds(COALESCE(channel_id, ''), name);
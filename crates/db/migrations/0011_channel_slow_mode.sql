@@ -0,0 +1,5 @@
+-- Speakeasy Migration v11
+-- Langsam-Modus pro Kanal: Mindestabstand in Sekunden zwischen zwei
+-- Chat-Nachrichten desselben Benutzers (0 = aus)
+
+ALTER TABLE channels ADD COLUMN slow_mode_secs INTEGER NOT NULL DEFAULT 0;
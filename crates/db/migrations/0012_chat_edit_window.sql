@@ -0,0 +1,9 @@
+-- Speakeasy Migration v12
+-- Bearbeitungsfrist fuer Chat-Nachrichten: Sekunden nach dem Senden, in
+-- denen Verfasser ihre Nachrichten editieren oder loeschen duerfen
+-- (NULL = Server-Standard, 0 = unbegrenzt). Editierte Nachrichten zaehlen
+-- ihre Bearbeitungen fuer Moderations-Auswertungen.
+
+ALTER TABLE channels ADD COLUMN edit_window_secs INTEGER;
+
+ALTER TABLE chat_messages ADD COLUMN edit_count INTEGER NOT NULL DEFAULT 0;
@@ -0,0 +1,20 @@
+-- Speakeasy Migration v13
+-- Moderations-Bereinigung: entfernt die Nachrichten und Dateien eines
+-- Benutzers aus einem Zeitfenster (z.B. nach einem Spam-Bann). Die Zeile
+-- haelt Auftrag und Fortschritt, damit eine abgebrochene Bereinigung mit
+-- derselben ID fortgesetzt werden kann.
+-- Alle Zeitpunkte in UTC ('YYYY-MM-DDTHH:MM:SSZ', per Textvergleich sortierbar)
+
+CREATE TABLE IF NOT EXISTS content_cleanups (
+    id            TEXT PRIMARY KEY NOT NULL,
+    user_id       TEXT NOT NULL,            -- kein Fremdschluessel: Auftrag ueberlebt das Konto
+    since         TEXT NOT NULL,            -- nur Inhalte mit created_at >= since
+    channel_id    TEXT,                     -- NULL = alle Kanaele
+    requested_by  TEXT,
+    messages      INTEGER NOT NULL DEFAULT 0,
+    files         INTEGER NOT NULL DEFAULT 0,
+    created_at    TEXT NOT NULL,
+    completed_at  TEXT
+);
+
+CREATE INDEX IF NOT EXISTS idx_content_cleanups_user ON content_cleanups(user_id, created_at);
@@ -0,0 +1,20 @@
+-- Speakeasy Migration v14
+-- Angemeldete Datei-Uploads: der Client erhaelt per Control-Verbindung
+-- einen signierten Upload-Link und uebertraegt die Datei danach per HTTP.
+-- Bis dahin steht sie nur hier; beim Abschluss wird die Zeile in `files`
+-- uebernommen (gleiche ID) und entfernt.
+-- Alle Zeitpunkte in UTC ('YYYY-MM-DDTHH:MM:SSZ', per Textvergleich sortierbar)
+
+CREATE TABLE IF NOT EXISTS file_uploads (
+    id          TEXT PRIMARY KEY NOT NULL,  -- spaetere files.id
+    channel_id  TEXT NOT NULL REFERENCES channels(id) ON DELETE CASCADE,
+    uploader_id TEXT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    filename    TEXT NOT NULL,
+    mime_type   TEXT NOT NULL,
+    size_bytes  INTEGER NOT NULL,
+    checksum    TEXT,                       -- vom Client angekuendigter SHA-256 (optional)
+    created_at  TEXT NOT NULL,
+    expires_at  TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_file_uploads_expires_at ON file_uploads(expires_at);
@@ -0,0 +1,5 @@
+-- Speakeasy Migration v15
+-- Beitritt nur mit Freigabe: Benutzer ohne Ausnahme klopfen an
+-- (ChannelKnock), Mitglieder mit b_channel_join_approve lassen sie ein
+
+ALTER TABLE channels ADD COLUMN join_by_approval INTEGER NOT NULL DEFAULT 0;
@@ -0,0 +1,13 @@
+-- Speakeasy Migration v16
+-- Neustart-Protokoll: ein Eintrag pro Serverstart, damit Betreiber sehen,
+-- wann und nach welchem Shutdown (sauber oder Absturz) neu gestartet wurde
+
+CREATE TABLE IF NOT EXISTS server_starts (
+    id              TEXT PRIMARY KEY NOT NULL,
+    started_at      TEXT NOT NULL,
+    version         TEXT NOT NULL,
+    clean_shutdown  INTEGER NOT NULL,
+    config_hash     TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_server_starts_started_at ON server_starts(started_at);
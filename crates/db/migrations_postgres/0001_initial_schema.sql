@@ -0,0 +1,323 @@
+-- Speakeasy Datenbank-Schema fuer PostgreSQL
+-- Entspricht dem SQLite-Schema nach Migration 11 (migrations/0001 bis 0011).
+-- Unterschiede zum SQLite-Schema:
+--   - UUIDs als UUID, Zeitpunkte als TIMESTAMPTZ, Flags als BOOLEAN
+--   - JSON-Spalten als JSONB
+--   - tri_state als BOOLEAN: NULL=skip, TRUE=grant, FALSE=deny
+--   - seq (Identity) ersetzt die SQLite-rowid als Einfuege-Reihenfolge
+
+-- Benutzer
+CREATE TABLE IF NOT EXISTS users (
+    id               UUID PRIMARY KEY NOT NULL,
+    username         TEXT UNIQUE NOT NULL,
+    password_hash    TEXT NOT NULL,
+    created_at       TIMESTAMPTZ NOT NULL DEFAULT now(),
+    last_login       TIMESTAMPTZ,
+    is_active        BOOLEAN NOT NULL DEFAULT TRUE,
+    password_changed BOOLEAN NOT NULL DEFAULT FALSE
+);
+
+CREATE INDEX IF NOT EXISTS idx_users_is_active ON users(is_active);
+
+-- Kanaele
+CREATE TABLE IF NOT EXISTS channels (
+    id              UUID PRIMARY KEY NOT NULL,
+    name            TEXT NOT NULL,
+    parent_id       UUID REFERENCES channels(id) ON DELETE SET NULL,
+    topic           TEXT,
+    password_hash   TEXT,
+    max_clients     BIGINT NOT NULL DEFAULT 0,  -- 0 = unbegrenzt
+    is_default      BOOLEAN NOT NULL DEFAULT FALSE,
+    sort_order      BIGINT NOT NULL DEFAULT 0,
+    channel_type    TEXT NOT NULL DEFAULT 'voice' CHECK(channel_type IN ('voice', 'text')),
+    codec_profile   TEXT,                       -- Name eines Audio-Presets, NULL = Standard
+    slow_mode_secs  BIGINT NOT NULL DEFAULT 0,  -- Langsam-Modus, 0 = aus
+    created_at      TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX IF NOT EXISTS idx_channels_parent_id ON channels(parent_id);
+CREATE INDEX IF NOT EXISTS idx_channels_sort_order ON channels(sort_order);
+
+-- Server-Gruppen (additive, mehrere pro User)
+CREATE TABLE IF NOT EXISTS server_groups (
+    id          UUID PRIMARY KEY NOT NULL,
+    name        TEXT UNIQUE NOT NULL,
+    priority    BIGINT NOT NULL DEFAULT 0,
+    is_default  BOOLEAN NOT NULL DEFAULT FALSE,
+    permissions JSONB NOT NULL DEFAULT '{}'
+);
+
+-- Kanal-Gruppen (eine pro User pro Kanal)
+CREATE TABLE IF NOT EXISTS channel_groups (
+    id          UUID PRIMARY KEY NOT NULL,
+    name        TEXT UNIQUE NOT NULL,
+    permissions JSONB NOT NULL DEFAULT '{}'
+);
+
+-- User <-> Server-Gruppen (many-to-many)
+CREATE TABLE IF NOT EXISTS user_server_groups (
+    user_id     UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    group_id    UUID NOT NULL REFERENCES server_groups(id) ON DELETE CASCADE,
+    PRIMARY KEY (user_id, group_id)
+);
+
+-- User <-> Kanal-Gruppen (eine pro User pro Kanal)
+CREATE TABLE IF NOT EXISTS user_channel_groups (
+    user_id     UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    channel_id  UUID NOT NULL REFERENCES channels(id) ON DELETE CASCADE,
+    group_id    UUID NOT NULL REFERENCES channel_groups(id) ON DELETE CASCADE,
+    PRIMARY KEY (user_id, channel_id)
+);
+
+-- Berechtigungen (flexibles Permission-System)
+-- target_type: 'user' | 'server_group' | 'channel_group' | 'server_default' | 'channel_default'
+-- value_type: 'tri_state' | 'int_limit' | 'scope'
+CREATE TABLE IF NOT EXISTS permissions (
+    id              UUID PRIMARY KEY NOT NULL,
+    target_type     TEXT NOT NULL,
+    target_id       UUID,           -- NULL fuer Server-Default
+    permission_key  TEXT NOT NULL,
+    value_type      TEXT NOT NULL DEFAULT 'tri_state' CHECK(value_type IN ('tri_state', 'int_limit', 'scope')),
+    tri_state       BOOLEAN,        -- NULL=skip, TRUE=grant, FALSE=deny
+    int_limit       BIGINT,         -- fuer IntLimit-Typ
+    scope_json      JSONB,          -- JSON-Array fuer Scope-Typ
+    channel_id      UUID REFERENCES channels(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_permissions_target ON permissions(target_type, target_id);
+CREATE INDEX IF NOT EXISTS idx_permissions_key ON permissions(permission_key);
+CREATE INDEX IF NOT EXISTS idx_permissions_channel ON permissions(channel_id);
+
+-- Bans
+CREATE TABLE IF NOT EXISTS bans (
+    id          UUID PRIMARY KEY NOT NULL,
+    user_id     UUID REFERENCES users(id) ON DELETE SET NULL,  -- NULL wenn nach IP gebannt
+    ip          TEXT,
+    reason      TEXT NOT NULL DEFAULT '',
+    banned_by   UUID REFERENCES users(id) ON DELETE SET NULL,
+    expires_at  TIMESTAMPTZ,    -- NULL = permanent
+    created_at  TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX IF NOT EXISTS idx_bans_user_id ON bans(user_id);
+CREATE INDEX IF NOT EXISTS idx_bans_ip ON bans(ip);
+CREATE INDEX IF NOT EXISTS idx_bans_expires_at ON bans(expires_at);
+
+-- Audit-Log
+CREATE TABLE IF NOT EXISTS audit_log (
+    id           UUID PRIMARY KEY NOT NULL,
+    seq          BIGINT GENERATED ALWAYS AS IDENTITY,
+    actor_id     UUID REFERENCES users(id) ON DELETE SET NULL,
+    action       TEXT NOT NULL,
+    target_type  TEXT,
+    target_id    TEXT,
+    details_json JSONB NOT NULL DEFAULT '{}',
+    timestamp    TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX IF NOT EXISTS idx_audit_log_actor ON audit_log(actor_id);
+CREATE INDEX IF NOT EXISTS idx_audit_log_action ON audit_log(action);
+CREATE INDEX IF NOT EXISTS idx_audit_log_timestamp ON audit_log(timestamp);
+CREATE INDEX IF NOT EXISTS idx_audit_log_target ON audit_log(target_type, target_id);
+
+-- Einladungen
+CREATE TABLE IF NOT EXISTS invites (
+    id                UUID PRIMARY KEY NOT NULL,
+    code              TEXT UNIQUE NOT NULL,
+    channel_id        UUID REFERENCES channels(id) ON DELETE CASCADE,
+    assigned_group_id UUID REFERENCES server_groups(id) ON DELETE SET NULL,
+    max_uses          BIGINT NOT NULL DEFAULT 0,  -- 0 = unbegrenzt
+    used_count        BIGINT NOT NULL DEFAULT 0,
+    expires_at        TIMESTAMPTZ,                -- NULL = nie
+    created_by        UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    created_at        TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX IF NOT EXISTS idx_invites_created_by ON invites(created_by);
+CREATE INDEX IF NOT EXISTS idx_invites_expires_at ON invites(expires_at);
+
+-- API-Token fuer Bots und Commander-Clients
+CREATE TABLE IF NOT EXISTS api_tokens (
+    id              UUID PRIMARY KEY NOT NULL,
+    user_id         UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    beschreibung    TEXT NOT NULL DEFAULT '',
+    scopes_json     JSONB NOT NULL DEFAULT '[]',
+    token_hash      TEXT NOT NULL,                  -- Argon2id-Hash des Token-Werts
+    token_praefix   TEXT NOT NULL,                  -- Erste 8 Zeichen zur Anzeige
+    erstellt_am     TIMESTAMPTZ NOT NULL DEFAULT now(),
+    laeuft_ab_am    TIMESTAMPTZ,                    -- NULL = nie ablaufend
+    widerrufen      BOOLEAN NOT NULL DEFAULT FALSE
+);
+
+CREATE INDEX IF NOT EXISTS idx_api_tokens_user_id ON api_tokens(user_id);
+
+-- Chat-Nachrichten
+CREATE TABLE IF NOT EXISTS chat_messages (
+    id           UUID PRIMARY KEY NOT NULL,
+    seq          BIGINT GENERATED ALWAYS AS IDENTITY,
+    channel_id   UUID NOT NULL REFERENCES channels(id) ON DELETE CASCADE,
+    sender_id    UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    content      TEXT NOT NULL,
+    message_type TEXT NOT NULL DEFAULT 'text' CHECK(message_type IN ('text', 'file', 'system')),
+    reply_to     UUID REFERENCES chat_messages(id) ON DELETE SET NULL,
+    created_at   TIMESTAMPTZ NOT NULL DEFAULT now(),
+    edited_at    TIMESTAMPTZ,
+    deleted_at   TIMESTAMPTZ
+);
+
+CREATE INDEX IF NOT EXISTS idx_chat_messages_channel_id ON chat_messages(channel_id, created_at);
+CREATE INDEX IF NOT EXISTS idx_chat_messages_sender_id ON chat_messages(sender_id);
+
+-- Dateien
+CREATE TABLE IF NOT EXISTS files (
+    id           UUID PRIMARY KEY NOT NULL,
+    seq          BIGINT GENERATED ALWAYS AS IDENTITY,
+    channel_id   UUID NOT NULL REFERENCES channels(id) ON DELETE CASCADE,
+    uploader_id  UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    filename     TEXT NOT NULL,
+    mime_type    TEXT NOT NULL,
+    size_bytes   BIGINT NOT NULL,
+    storage_path TEXT NOT NULL,
+    checksum     TEXT NOT NULL, -- SHA-256
+    created_at   TIMESTAMPTZ NOT NULL DEFAULT now(),
+    deleted_at   TIMESTAMPTZ
+);
+
+CREATE INDEX IF NOT EXISTS idx_files_channel_id ON files(channel_id);
+CREATE INDEX IF NOT EXISTS idx_files_uploader_id ON files(uploader_id);
+
+-- Datei-Kontingente pro Gruppe
+CREATE TABLE IF NOT EXISTS file_quotas (
+    group_id          TEXT NOT NULL PRIMARY KEY,
+    max_file_size     BIGINT NOT NULL DEFAULT 10485760,    -- 10 MB
+    max_total_storage BIGINT NOT NULL DEFAULT 1073741824,  -- 1 GB
+    current_usage     BIGINT NOT NULL DEFAULT 0
+);
+
+-- Zugriffsprotokoll fuer Datei-Downloads (append-only)
+-- user_id ist NULL wenn der Server im Aggregat-Modus protokolliert
+CREATE TABLE IF NOT EXISTS file_access_log (
+    id           UUID PRIMARY KEY NOT NULL,
+    seq          BIGINT GENERATED ALWAYS AS IDENTITY,
+    file_id      UUID NOT NULL REFERENCES files(id) ON DELETE CASCADE,
+    user_id      UUID REFERENCES users(id) ON DELETE SET NULL,
+    timestamp    TIMESTAMPTZ NOT NULL DEFAULT now(),
+    bytes_served BIGINT NOT NULL DEFAULT 0,
+    completed    BOOLEAN NOT NULL DEFAULT FALSE
+);
+
+CREATE INDEX IF NOT EXISTS idx_file_access_log_file_id ON file_access_log(file_id);
+CREATE INDEX IF NOT EXISTS idx_file_access_log_user_id ON file_access_log(user_id);
+CREATE INDEX IF NOT EXISTS idx_file_access_log_timestamp ON file_access_log(timestamp);
+
+-- Kanal-Vorlagen: wiederverwendbare Kanal-Teilbaeume (JSON-Definition)
+CREATE TABLE IF NOT EXISTS channel_templates (
+    id              UUID PRIMARY KEY NOT NULL,
+    name            TEXT UNIQUE NOT NULL,
+    description     TEXT,
+    definition_json JSONB NOT NULL,                 -- Wurzelknoten des Teilbaums
+    builtin         BOOLEAN NOT NULL DEFAULT FALSE, -- mitgeliefert, nicht loeschbar
+    created_by      UUID REFERENCES users(id) ON DELETE SET NULL,
+    created_at      TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+-- Mitgelieferte Beispiel-Vorlagen (wie SQLite-Migration 6)
+INSERT INTO channel_templates (id, name, description, definition_json, builtin)
+VALUES (
+    '00000000-0000-4000-8000-00000000a001',
+    'Team-Kategorie',
+    'Kategorie mit Lobby, zwei Team-Kanaelen und AFK-Kanal',
+    '{"name":"Teams","codec_profile":"speech","children":[
+        {"name":"Lobby"},
+        {"name":"Team 1","max_clients":5},
+        {"name":"Team 2","max_clients":5},
+        {"name":"AFK","codec_profile":"low_bandwidth","permissions":[
+            {"target":{"type":"channel_default"},"permission_key":"b_client_poke","wert":{"type":"TriState","value":"Deny"}}
+        ]}
+    ]}',
+    TRUE
+)
+ON CONFLICT DO NOTHING;
+
+INSERT INTO channel_templates (id, name, description, definition_json, builtin)
+VALUES (
+    '00000000-0000-4000-8000-00000000a002',
+    'Community-Bereich',
+    'Ankuendigungen (Text), Plauder- und Musik-Kanal',
+    '{"name":"Community","children":[
+        {"name":"Ankuendigungen","channel_type":"text","topic":"Neuigkeiten vom Team"},
+        {"name":"Plaudern","codec_profile":"balanced"},
+        {"name":"Musik","codec_profile":"music","max_clients":10}
+    ]}',
+    TRUE
+)
+ON CONFLICT DO NOTHING;
+
+-- Zur Laufzeit aenderbare Server-Einstellungen (Schluessel/Wert, Werte als Text)
+CREATE TABLE IF NOT EXISTS server_settings (
+    key         TEXT PRIMARY KEY NOT NULL,
+    value       TEXT NOT NULL,
+    updated_at  TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+-- Geplante Aktionen des Zeitplaners
+-- time_zone gilt nur fuer die Auswertung des Cron-Ausdrucks
+CREATE TABLE IF NOT EXISTS scheduled_actions (
+    id          UUID PRIMARY KEY NOT NULL,
+    name        TEXT NOT NULL,
+    action_json JSONB NOT NULL,                   -- Aktion inkl. Typ und Nutzdaten
+    run_at      TIMESTAMPTZ,                      -- einmaliger Zeitpunkt (sonst NULL)
+    cron        TEXT,                             -- Cron-Ausdruck (sonst NULL)
+    time_zone   TEXT NOT NULL DEFAULT 'UTC',      -- IANA-Zeitzone
+    next_run_at TIMESTAMPTZ,                      -- NULL = erledigt oder abgebrochen
+    last_run_at TIMESTAMPTZ,
+    created_by  UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    scopes_json JSONB,                            -- Token-Scopes; NULL = Session
+    cancelled   BOOLEAN NOT NULL DEFAULT FALSE,
+    created_at  TIMESTAMPTZ NOT NULL DEFAULT now(),
+    CHECK ((run_at IS NULL) <> (cron IS NULL))
+);
+
+CREATE INDEX IF NOT EXISTS idx_scheduled_actions_next_run
+    ON scheduled_actions(next_run_at) WHERE NOT cancelled;
+
+-- Export-Archive: abholbar genau einmal ueber einen Token (gespeichert wird
+-- nur dessen SHA-256); die Zeile begrenzt zugleich die Export-Haeufigkeit
+CREATE TABLE IF NOT EXISTS account_exports (
+    id            UUID PRIMARY KEY NOT NULL,
+    user_id       UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    token_hash    TEXT UNIQUE NOT NULL,
+    storage_path  TEXT NOT NULL,
+    size_bytes    BIGINT,                   -- NULL solange das Archiv erstellt wird
+    created_at    TIMESTAMPTZ NOT NULL,
+    completed_at  TIMESTAMPTZ,
+    expires_at    TIMESTAMPTZ NOT NULL,
+    downloaded_at TIMESTAMPTZ
+);
+
+CREATE INDEX IF NOT EXISTS idx_account_exports_user ON account_exports(user_id, created_at);
+CREATE INDEX IF NOT EXISTS idx_account_exports_expires ON account_exports(expires_at);
+
+-- Platzhalter-Absender fuer Nachrichten geloeschter Konten
+INSERT INTO users (id, username, password_hash, is_active, password_changed)
+VALUES ('00000000-0000-0000-0000-000000000000', '[geloescht]', '!', FALSE, TRUE)
+ON CONFLICT DO NOTHING;
+
+-- Soundboard: kurze Clips, bei der Registrierung einmalig nach Opus kodiert
+-- frames enthaelt die Opus-Frames hintereinander, jeweils mit 2 Byte Laenge
+-- (Big Endian) davor; channel_id NULL = serverweit verfuegbar
+CREATE TABLE IF NOT EXISTS soundboard_sounds (
+    id          UUID PRIMARY KEY NOT NULL,
+    name        TEXT NOT NULL,
+    channel_id  UUID REFERENCES channels(id) ON DELETE CASCADE,
+    file_id     UUID,                         -- Quelldatei (darf spaeter fehlen)
+    frame_ms    BIGINT NOT NULL,
+    duration_ms BIGINT NOT NULL,
+    frames      BYTEA NOT NULL,
+    created_by  UUID REFERENCES users(id) ON DELETE SET NULL,
+    created_at  TIMESTAMPTZ NOT NULL
+);
+
+-- Serverweite Sounds teilen sich einen Namensraum (NULL ist nie gleich NULL)
+CREATE UNIQUE INDEX IF NOT EXISTS idx_soundboard_sounds_name
+    ON soundboard_sounds(COALESCE(channel_id, '00000000-0000-0000-0000-000000000000'::uuid), name);
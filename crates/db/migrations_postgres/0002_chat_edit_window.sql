@@ -0,0 +1,8 @@
+-- Speakeasy PostgreSQL-Migration v2
+-- Entspricht SQLite-Migration 12: Bearbeitungsfrist pro Kanal
+-- (NULL = Server-Standard, 0 = unbegrenzt) und Zaehler fuer Bearbeitungen
+-- von Chat-Nachrichten.
+
+ALTER TABLE channels ADD COLUMN edit_window_secs BIGINT;
+
+ALTER TABLE chat_messages ADD COLUMN edit_count BIGINT NOT NULL DEFAULT 0;
@@ -0,0 +1,18 @@
+-- Speakeasy PostgreSQL-Migration v3
+-- Entspricht SQLite-Migration 13: Auftraege der Moderations-Bereinigung
+-- mit Fortschritt, damit abgebrochene Bereinigungen fortgesetzt werden
+-- koennen.
+
+CREATE TABLE IF NOT EXISTS content_cleanups (
+    id            UUID PRIMARY KEY NOT NULL,
+    user_id       UUID NOT NULL,            -- kein Fremdschluessel: Auftrag ueberlebt das Konto
+    since         TIMESTAMPTZ NOT NULL,     -- nur Inhalte mit created_at >= since
+    channel_id    UUID,                     -- NULL = alle Kanaele
+    requested_by  UUID,
+    messages      BIGINT NOT NULL DEFAULT 0,
+    files         BIGINT NOT NULL DEFAULT 0,
+    created_at    TIMESTAMPTZ NOT NULL,
+    completed_at  TIMESTAMPTZ
+);
+
+CREATE INDEX IF NOT EXISTS idx_content_cleanups_user ON content_cleanups(user_id, created_at);
@@ -0,0 +1,17 @@
+-- Speakeasy PostgreSQL-Migration v4
+-- Entspricht SQLite-Migration 14: angemeldete Datei-Uploads bis zur
+-- Uebertragung per HTTP.
+
+CREATE TABLE IF NOT EXISTS file_uploads (
+    id          UUID PRIMARY KEY NOT NULL,  -- spaetere files.id
+    channel_id  UUID NOT NULL REFERENCES channels(id) ON DELETE CASCADE,
+    uploader_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    filename    TEXT NOT NULL,
+    mime_type   TEXT NOT NULL,
+    size_bytes  BIGINT NOT NULL,
+    checksum    TEXT,                       -- vom Client angekuendigter SHA-256 (optional)
+    created_at  TIMESTAMPTZ NOT NULL,
+    expires_at  TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_file_uploads_expires_at ON file_uploads(expires_at);
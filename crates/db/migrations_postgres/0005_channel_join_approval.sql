@@ -0,0 +1,5 @@
+-- Speakeasy PostgreSQL-Migration v5
+-- Entspricht SQLite-Migration 15: Beitritt nur mit Freigabe durch
+-- Kanalmitglieder
+
+ALTER TABLE channels ADD COLUMN join_by_approval BOOLEAN NOT NULL DEFAULT FALSE;
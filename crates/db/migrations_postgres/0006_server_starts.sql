@@ -0,0 +1,13 @@
+-- Speakeasy PostgreSQL-Migration v6
+-- Entspricht SQLite-Migration 16: Neustart-Protokoll mit einem Eintrag pro
+-- Serverstart
+
+CREATE TABLE IF NOT EXISTS server_starts (
+    id              UUID PRIMARY KEY,
+    started_at      TIMESTAMPTZ NOT NULL,
+    version         TEXT NOT NULL,
+    clean_shutdown  BOOLEAN NOT NULL,
+    config_hash     TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_server_starts_started_at ON server_starts(started_at);
@@ -0,0 +1,480 @@
+//! Gepuffertes Schreiben des Audit-Logs
+//!
+//! Jedes Ereignis einzeln zu schreiben haelt pro Aktion die SQLite-Schreibsperre.
+//! [`AuditPuffer`] sammelt Ereignisse in einer begrenzten Queue und schreibt
+//! sie gebuendelt in einer Transaktion – sobald `batch_groesse` Ereignisse
+//! anstehen oder `intervall` seit dem aeltesten vergangen ist.
+//!
+//! - Ist die Queue voll, wird das Ereignis verworfen. Beim naechsten
+//!   Schreiben folgt ein Meta-Ereignis [`VERWORFEN_AKTION`] mit der Anzahl.
+//! - Sicherheitskritische Ereignisse (Bans, Berechtigungen) laufen ueber
+//!   [`AuditSink::sicher_protokollieren`]: sie warten auf Platz in der Queue
+//!   und kehren erst zurueck, wenn sie (samt allen vorherigen) gespeichert sind.
+//! - [`AuditPuffer::leeren`] schreibt alles Ausstehende; der Server ruft es
+//!   beim Herunterfahren auf.
+//!
+//! Alle Ereignisse laufen durch eine einzige Queue, die Reihenfolge bleibt
+//! also erhalten.
+
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use tokio::sync::{mpsc, oneshot};
+use tokio::time::Instant;
+
+use crate::error::DbError;
+use crate::models::NeuerAuditEintrag;
+use crate::repository::{AuditLogRepository, DbResult};
+
+/// Aktion des Meta-Ereignisses fuer verworfene Ereignisse
+pub const VERWORFEN_AKTION: &str = "audit.ereignisse_verworfen";
+
+/// Future von [`AuditSink::sicher_protokollieren`]
+pub type SinkFuture<'a> = Pin<Box<dyn Future<Output = DbResult<()>> + Send + 'a>>;
+
+/// Ziel fuer Audit-Ereignisse
+///
+/// Abfragen laufen weiterhin direkt ueber das [`AuditLogRepository`].
+pub trait AuditSink: Send + Sync {
+    /// Reiht ein Ereignis ein, ohne zu warten
+    fn protokollieren(&self, eintrag: NeuerAuditEintrag);
+
+    /// Schreibt ein Ereignis, das nicht verloren gehen darf
+    ///
+    /// Kehrt erst zurueck, wenn das Ereignis gespeichert ist.
+    fn sicher_protokollieren(&self, eintrag: NeuerAuditEintrag) -> SinkFuture<'_>;
+}
+
+/// Konfiguration des Audit-Puffers
+#[derive(Debug, Clone)]
+pub struct AuditPufferKonfig {
+    /// Maximale Anzahl wartender Ereignisse
+    pub queue_groesse: usize,
+    /// Ab so vielen Ereignissen wird sofort geschrieben
+    pub batch_groesse: usize,
+    /// Hoechste Wartezeit eines Ereignisses bis zum Schreiben
+    pub intervall: Duration,
+}
+
+impl Default for AuditPufferKonfig {
+    fn default() -> Self {
+        Self {
+            queue_groesse: 4096,
+            batch_groesse: 50,
+            intervall: Duration::from_millis(250),
+        }
+    }
+}
+
+/// Nachricht an den Writer-Thread
+enum WriterNachricht {
+    Eintrag(NeuerAuditEintrag),
+    /// Wird zusammen mit allen vorherigen sofort geschrieben
+    Sicher(NeuerAuditEintrag, oneshot::Sender<DbResult<()>>),
+    /// Bestaetigt, sobald alle vorherigen Ereignisse geschrieben wurden
+    Leeren(oneshot::Sender<()>),
+}
+
+/// Zaehler fuer verlorene Ereignisse
+#[derive(Default)]
+struct Verluste {
+    /// Noch nicht per Meta-Ereignis gemeldet
+    offen: AtomicU64,
+    /// Seit dem Start
+    gesamt: AtomicU64,
+}
+
+impl Verluste {
+    fn zaehlen(&self, anzahl: u64) {
+        self.offen.fetch_add(anzahl, Ordering::Relaxed);
+        self.gesamt.fetch_add(anzahl, Ordering::Relaxed);
+    }
+}
+
+/// Gepufferter [`AuditSink`] mit eigenem Writer-Thread
+pub struct AuditPuffer {
+    tx: mpsc::Sender<WriterNachricht>,
+    verluste: Arc<Verluste>,
+}
+
+impl AuditPuffer {
+    /// Startet den Writer-Thread und gibt den Puffer zurueck
+    ///
+    /// Muss innerhalb einer tokio-Runtime aufgerufen werden.
+    pub fn starten<A>(audit_repo: Arc<A>, konfig: AuditPufferKonfig) -> std::io::Result<Arc<Self>>
+    where
+        A: AuditLogRepository + 'static,
+    {
+        let (tx, rx) = mpsc::channel(konfig.queue_groesse.max(1));
+        let verluste = Arc::new(Verluste::default());
+
+        // Eigener Thread auf der aktuellen Runtime, da die Repository-Futures
+        // (async_fn_in_trait) keine Send-Garantie fuer tokio::spawn bieten.
+        let handle = tokio::runtime::Handle::current();
+        let writer = Writer {
+            audit_repo,
+            batch_groesse: konfig.batch_groesse.max(1),
+            intervall: konfig.intervall,
+            puffer: Vec::new(),
+            letzter_zeitstempel: None,
+            verluste: Arc::clone(&verluste),
+        };
+        std::thread::Builder::new()
+            .name("audit-writer".into())
+            .spawn(move || handle.block_on(writer.laufen(rx)))?;
+
+        Ok(Arc::new(Self { tx, verluste }))
+    }
+
+    /// Wartet bis alle bisher eingereihten Ereignisse geschrieben wurden
+    pub async fn leeren(&self) {
+        let (bestaetigung_tx, bestaetigung_rx) = oneshot::channel();
+        if self
+            .tx
+            .send(WriterNachricht::Leeren(bestaetigung_tx))
+            .await
+            .is_ok()
+        {
+            let _ = bestaetigung_rx.await;
+        }
+    }
+
+    /// Anzahl seit dem Start verlorener Ereignisse
+    pub fn verworfen(&self) -> u64 {
+        self.verluste.gesamt.load(Ordering::Relaxed)
+    }
+}
+
+impl AuditSink for AuditPuffer {
+    fn protokollieren(&self, eintrag: NeuerAuditEintrag) {
+        if let Err(e) = self.tx.try_send(WriterNachricht::Eintrag(eintrag)) {
+            let (grund, eintrag) = match e {
+                mpsc::error::TrySendError::Full(WriterNachricht::Eintrag(e)) => ("Queue voll", e),
+                mpsc::error::TrySendError::Closed(WriterNachricht::Eintrag(e)) => {
+                    ("Writer beendet", e)
+                }
+                _ => unreachable!("nur Eintraege werden ohne Warten gesendet"),
+            };
+            self.verluste.zaehlen(1);
+            tracing::warn!(aktion = %eintrag.action, grund, "Audit-Ereignis verworfen");
+        }
+    }
+
+    fn sicher_protokollieren(&self, eintrag: NeuerAuditEintrag) -> SinkFuture<'_> {
+        Box::pin(async move {
+            let beendet = || DbError::intern("Audit-Writer beendet");
+            let (bestaetigung_tx, bestaetigung_rx) = oneshot::channel();
+            self.tx
+                .send(WriterNachricht::Sicher(eintrag, bestaetigung_tx))
+                .await
+                .map_err(|_| beendet())?;
+            bestaetigung_rx.await.map_err(|_| beendet())?
+        })
+    }
+}
+
+/// Zustand des Writer-Threads
+struct Writer<A> {
+    audit_repo: Arc<A>,
+    batch_groesse: usize,
+    intervall: Duration,
+    puffer: Vec<NeuerAuditEintrag>,
+    /// Zeitstempel des zuletzt geschriebenen Ereignisses
+    letzter_zeitstempel: Option<DateTime<Utc>>,
+    verluste: Arc<Verluste>,
+}
+
+impl<A: AuditLogRepository> Writer<A> {
+    async fn laufen(mut self, mut rx: mpsc::Receiver<WriterNachricht>) {
+        // Frist des aeltesten gepufferten Ereignisses
+        let mut frist: Option<Instant> = None;
+
+        loop {
+            let nachricht = match frist {
+                Some(f) => match tokio::time::timeout_at(f, rx.recv()).await {
+                    Ok(nachricht) => nachricht,
+                    Err(_) => {
+                        let _ = self.schreiben().await;
+                        frist = None;
+                        continue;
+                    }
+                },
+                None => rx.recv().await,
+            };
+
+            match nachricht {
+                Some(WriterNachricht::Eintrag(eintrag)) => {
+                    self.puffer.push(eintrag);
+                    if self.puffer.len() >= self.batch_groesse {
+                        let _ = self.schreiben().await;
+                        frist = None;
+                    } else if frist.is_none() {
+                        frist = Some(Instant::now() + self.intervall);
+                    }
+                }
+                Some(WriterNachricht::Sicher(eintrag, bestaetigung)) => {
+                    self.puffer.push(eintrag);
+                    let _ = bestaetigung.send(self.schreiben().await);
+                    frist = None;
+                }
+                Some(WriterNachricht::Leeren(bestaetigung)) => {
+                    let _ = self.schreiben().await;
+                    frist = None;
+                    let _ = bestaetigung.send(());
+                }
+                None => {
+                    let _ = self.schreiben().await;
+                    break;
+                }
+            }
+        }
+
+        tracing::debug!("Audit-Writer beendet");
+    }
+
+    /// Schreibt den Puffer (und ggf. das Verlust-Ereignis) in einer Transaktion
+    ///
+    /// Bei einem Fehler gelten die Ereignisse als verloren und werden gezaehlt.
+    async fn schreiben(&mut self) -> DbResult<()> {
+        let verworfen = self.verluste.offen.swap(0, Ordering::Relaxed);
+        if verworfen > 0 {
+            self.puffer.push(NeuerAuditEintrag::neu(
+                None,
+                VERWORFEN_AKTION,
+                None,
+                None,
+                serde_json::json!({ "anzahl": verworfen }),
+            ));
+        }
+        if self.puffer.is_empty() {
+            return Ok(());
+        }
+
+        // Gleiche oder rueckwaerts laufende Zeitstempel wuerden die
+        // Reihenfolge in Abfragen (sortiert nach Zeit) verwischen
+        for eintrag in &mut self.puffer {
+            if let Some(letzter) = self.letzter_zeitstempel {
+                if eintrag.timestamp <= letzter {
+                    eintrag.timestamp = letzter + chrono::Duration::microseconds(1);
+                }
+            }
+            self.letzter_zeitstempel = Some(eintrag.timestamp);
+        }
+
+        let ergebnis = self.audit_repo.log_events(&self.puffer).await;
+        if let Err(e) = &ergebnis {
+            // Meta-Ereignis nicht doppelt zaehlen: es wird neu erzeugt
+            let verloren = self.puffer.len() as u64 - u64::from(verworfen > 0);
+            self.verluste.offen.fetch_add(verworfen, Ordering::Relaxed);
+            self.verluste.zaehlen(verloren);
+            tracing::error!(%e, anzahl = verloren, "Audit-Ereignisse konnten nicht geschrieben werden");
+        }
+        self.puffer.clear();
+        ergebnis
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{AuditLogFilter, AuditLogRecord};
+    use crate::SqliteDb;
+    use std::sync::Mutex;
+    use tokio::sync::Notify;
+    use uuid::Uuid;
+
+    /// Zeichnet die Groesse jedes Sammel-Schreibvorgangs auf; kann angehalten werden
+    struct BeobachtetesRepo {
+        db: SqliteDb,
+        schreibvorgaenge: Mutex<Vec<usize>>,
+        angehalten: Mutex<bool>,
+        gestartet: Notify,
+        weiter: Notify,
+    }
+
+    impl BeobachtetesRepo {
+        async fn neu() -> Arc<Self> {
+            Arc::new(Self {
+                db: SqliteDb::in_memory().await.unwrap(),
+                schreibvorgaenge: Mutex::new(Vec::new()),
+                angehalten: Mutex::new(false),
+                gestartet: Notify::new(),
+                weiter: Notify::new(),
+            })
+        }
+
+        fn schreibvorgaenge(&self) -> Vec<usize> {
+            self.schreibvorgaenge.lock().unwrap().clone()
+        }
+
+        async fn aktionen(&self) -> Vec<String> {
+            let mut ereignisse = self
+                .db
+                .list_events(AuditLogFilter::default())
+                .await
+                .unwrap();
+            // Neueste zuerst -> in Schreibreihenfolge bringen
+            ereignisse.reverse();
+            ereignisse.into_iter().map(|e| e.action).collect()
+        }
+    }
+
+    impl AuditLogRepository for BeobachtetesRepo {
+        async fn log_event(
+            &self,
+            actor_id: Option<Uuid>,
+            action: &str,
+            target_type: Option<&str>,
+            target_id: Option<&str>,
+            details: serde_json::Value,
+        ) -> DbResult<AuditLogRecord> {
+            self.db
+                .log_event(actor_id, action, target_type, target_id, details)
+                .await
+        }
+
+        async fn log_events(&self, eintraege: &[NeuerAuditEintrag]) -> DbResult<()> {
+            self.schreibvorgaenge.lock().unwrap().push(eintraege.len());
+            let angehalten = *self.angehalten.lock().unwrap();
+            if angehalten {
+                self.gestartet.notify_one();
+                self.weiter.notified().await;
+            }
+            self.db.log_events(eintraege).await
+        }
+
+        async fn list_events(&self, filter: AuditLogFilter) -> DbResult<Vec<AuditLogRecord>> {
+            self.db.list_events(filter).await
+        }
+
+        async fn count_events(&self, filter: AuditLogFilter) -> DbResult<i64> {
+            self.db.count_events(filter).await
+        }
+    }
+
+    fn ereignis(nr: usize) -> NeuerAuditEintrag {
+        NeuerAuditEintrag::neu(
+            None,
+            format!("test.{nr:03}"),
+            None,
+            None,
+            serde_json::json!({}),
+        )
+    }
+
+    fn konfig(batch_groesse: usize, intervall: Duration) -> AuditPufferKonfig {
+        AuditPufferKonfig {
+            queue_groesse: 1024,
+            batch_groesse,
+            intervall,
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn unter_last_wird_gebuendelt_und_reihenfolge_bleibt() {
+        let repo = BeobachtetesRepo::neu().await;
+        let puffer =
+            AuditPuffer::starten(Arc::clone(&repo), konfig(50, Duration::from_secs(3600))).unwrap();
+
+        for nr in 0..120 {
+            puffer.protokollieren(ereignis(nr));
+        }
+        puffer.leeren().await;
+
+        assert_eq!(repo.schreibvorgaenge(), vec![50, 50, 20]);
+        let erwartet: Vec<String> = (0..120).map(|nr| format!("test.{nr:03}")).collect();
+        assert_eq!(repo.aktionen().await, erwartet);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn nach_intervall_wird_geschrieben() {
+        let repo = BeobachtetesRepo::neu().await;
+        let puffer =
+            AuditPuffer::starten(Arc::clone(&repo), konfig(50, Duration::from_millis(20))).unwrap();
+
+        puffer.protokollieren(ereignis(1));
+        puffer.protokollieren(ereignis(2));
+        tokio::time::sleep(Duration::from_millis(300)).await;
+
+        assert_eq!(repo.schreibvorgaenge(), vec![2]);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn sichere_ereignisse_schreiben_sofort_mit_allen_vorherigen() {
+        let repo = BeobachtetesRepo::neu().await;
+        let puffer =
+            AuditPuffer::starten(Arc::clone(&repo), konfig(50, Duration::from_secs(3600))).unwrap();
+
+        puffer.protokollieren(ereignis(1));
+        puffer.sicher_protokollieren(ereignis(2)).await.unwrap();
+
+        assert_eq!(repo.schreibvorgaenge(), vec![2]);
+        assert_eq!(repo.aktionen().await, vec!["test.001", "test.002"]);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn leeren_beim_herunterfahren_schreibt_alles() {
+        let repo = BeobachtetesRepo::neu().await;
+        let puffer =
+            AuditPuffer::starten(Arc::clone(&repo), konfig(50, Duration::from_secs(3600))).unwrap();
+
+        for nr in 0..3 {
+            puffer.protokollieren(ereignis(nr));
+        }
+        assert!(repo.schreibvorgaenge().is_empty());
+
+        puffer.leeren().await;
+        assert_eq!(repo.aktionen().await.len(), 3);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn ueberlauf_wird_als_meta_ereignis_gemeldet() {
+        let repo = BeobachtetesRepo::neu().await;
+        *repo.angehalten.lock().unwrap() = true;
+        let puffer = AuditPuffer::starten(
+            Arc::clone(&repo),
+            AuditPufferKonfig {
+                queue_groesse: 2,
+                batch_groesse: 1,
+                intervall: Duration::from_secs(3600),
+            },
+        )
+        .unwrap();
+
+        // Erstes Ereignis haengt im Schreiben, zwei passen in die Queue
+        puffer.protokollieren(ereignis(0));
+        repo.gestartet.notified().await;
+        for nr in 1..6 {
+            puffer.protokollieren(ereignis(nr));
+        }
+        assert_eq!(puffer.verworfen(), 3);
+
+        *repo.angehalten.lock().unwrap() = false;
+        repo.weiter.notify_one();
+        puffer.leeren().await;
+
+        let aktionen = repo.aktionen().await;
+        assert_eq!(
+            aktionen,
+            vec![
+                "test.000",
+                "test.001",
+                "audit.ereignisse_verworfen",
+                "test.002"
+            ]
+        );
+        let meta = repo
+            .db
+            .list_events(AuditLogFilter {
+                action: Some(VERWORFEN_AKTION.into()),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(meta[0].details["anzahl"], 3);
+    }
+}
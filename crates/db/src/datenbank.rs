@@ -0,0 +1,816 @@
+//! Zur Laufzeit gewaehltes Datenbank-Backend
+//!
+//! [`Datenbank`] kapselt [`SqliteDb`] oder [`PostgresDb`] und implementiert
+//! alle Repository-Traits durch Weiterleitung. So braucht der Server nur einen
+//! konkreten Typ, unabhaengig davon, welches Backend die Konfiguration waehlt.
+
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use crate::error::DbError;
+use crate::models::{
+    AuditLogFilter, AuditLogRecord, BanRecord, BenutzerRecord, BenutzerUpdate, BerechtigungsWert,
+    BerechtigungsZiel, ChatNachrichtRecord, DateiKontingentRecord, DateiRecord, DateiZugriffFilter,
+    DateiZugriffRecord, EffektiveBerechtigung, EinladungRecord, EinstellungRecord,
+    GeplanteAktionRecord, ImportBenutzer, ImportServerGruppe, InhaltsBereinigungRecord,
+    InhaltsStapel, KanalFilter, KanalGruppeRecord, KanalRecord, KanalUpdate, KanalVorlageRecord,
+    KanalbaumGrenzen, KontoDaten, KontoExportRecord, KontoLoeschAuftrag, KontoLoeschung,
+    NachrichtenFilter, NachrichtenSuche, NeueDatei, NeueEinladung, NeueGeplanteAktion,
+    NeueInhaltsBereinigung, NeueKanalGruppe, NeueKanalVorlage, NeueNachricht, NeueServerGruppe,
+    NeuerAuditEintrag, NeuerBan, NeuerBenutzer, NeuerDateiZugriff, NeuerKanal, NeuerKontoExport,
+    NeuerServerStart, NeuerSound, NeuerUpload, ServerGruppeRecord, ServerStartRecord, SoundRecord,
+    UploadRecord, VorlagenKnoten,
+};
+use crate::permissions::BerechtigungsSpur;
+use crate::postgres::PostgresDb;
+use crate::repository::{
+    AuditLogRepository, BanRepository, ChannelGroupRepository, ChannelRepository,
+    ChannelTemplateRepository, ChatMessageRepository, DatabaseBackend, DatabaseConfig, DbResult,
+    FileRepository, ImportRepository, InviteRepository, KontoRepository, PermissionRepository,
+    ServerGroupRepository, ServerStartRepository, SettingsRepository, SoundboardRepository,
+    UserRepository, ZeitplanRepository,
+};
+use crate::sqlite::{MigrationsFortschritt, SqliteDb};
+
+/// SQLite- oder PostgreSQL-Datenbank, je nach [`DatabaseConfig::backend`]
+#[derive(Debug, Clone)]
+pub enum Datenbank {
+    Sqlite(SqliteDb),
+    Postgres(PostgresDb),
+}
+
+/// Leitet einen Aufruf an das gewaehlte Backend weiter
+macro_rules! weiterleiten {
+    ($self:ident, $methode:path $(, $arg:expr)*) => {
+        match $self {
+            Datenbank::Sqlite(db) => $methode(db $(, $arg)*).await,
+            Datenbank::Postgres(db) => $methode(db $(, $arg)*).await,
+        }
+    };
+}
+
+impl Datenbank {
+    /// Oeffnet das konfigurierte Backend und fuehrt Migrationen aus
+    pub async fn oeffnen(config: &DatabaseConfig) -> Result<Self, DbError> {
+        Self::oeffnen_mit_fortschritt(config, |_| {}).await
+    }
+
+    /// Wie [`oeffnen`](Self::oeffnen), meldet aber jede ausstehende Migration
+    pub async fn oeffnen_mit_fortschritt(
+        config: &DatabaseConfig,
+        fortschritt: impl FnMut(MigrationsFortschritt),
+    ) -> Result<Self, DbError> {
+        Ok(match config.backend {
+            DatabaseBackend::Sqlite => {
+                Self::Sqlite(SqliteDb::oeffnen_mit_fortschritt(config, fortschritt).await?)
+            }
+            DatabaseBackend::Postgres => {
+                Self::Postgres(PostgresDb::oeffnen_mit_fortschritt(config, fortschritt).await?)
+            }
+        })
+    }
+
+    /// Das verwendete Backend
+    pub fn backend(&self) -> DatabaseBackend {
+        match self {
+            Self::Sqlite(_) => DatabaseBackend::Sqlite,
+            Self::Postgres(_) => DatabaseBackend::Postgres,
+        }
+    }
+
+    /// Die SQLite-Datenbank, falls dieses Backend gewaehlt ist
+    ///
+    /// Fuer Funktionen, die nur mit einer Datenbank-Datei gehen (Sicherungen).
+    pub fn als_sqlite(&self) -> Option<&SqliteDb> {
+        match self {
+            Self::Sqlite(db) => Some(db),
+            Self::Postgres(_) => None,
+        }
+    }
+
+    /// Hoechste angewendete Schema-Version
+    pub async fn schema_version(&self) -> Result<i64, DbError> {
+        match self {
+            Self::Sqlite(db) => db.schema_version().await,
+            Self::Postgres(db) => db.schema_version().await,
+        }
+    }
+
+    /// Prueft mit einer trivialen Abfrage, ob die Datenbank antwortet
+    pub async fn erreichbar(&self) -> bool {
+        match self {
+            Self::Sqlite(db) => db.erreichbar().await,
+            Self::Postgres(db) => db.erreichbar().await,
+        }
+    }
+}
+
+impl From<SqliteDb> for Datenbank {
+    fn from(db: SqliteDb) -> Self {
+        Self::Sqlite(db)
+    }
+}
+
+impl From<PostgresDb> for Datenbank {
+    fn from(db: PostgresDb) -> Self {
+        Self::Postgres(db)
+    }
+}
+
+impl UserRepository for Datenbank {
+    async fn create(&self, data: NeuerBenutzer<'_>) -> DbResult<BenutzerRecord> {
+        weiterleiten!(self, UserRepository::create, data)
+    }
+
+    async fn get_by_id(&self, id: Uuid) -> DbResult<Option<BenutzerRecord>> {
+        weiterleiten!(self, UserRepository::get_by_id, id)
+    }
+
+    async fn get_by_name(&self, username: &str) -> DbResult<Option<BenutzerRecord>> {
+        weiterleiten!(self, UserRepository::get_by_name, username)
+    }
+
+    async fn update(&self, id: Uuid, data: BenutzerUpdate) -> DbResult<BenutzerRecord> {
+        weiterleiten!(self, UserRepository::update, id, data)
+    }
+
+    async fn delete(&self, id: Uuid) -> DbResult<bool> {
+        weiterleiten!(self, UserRepository::delete, id)
+    }
+
+    async fn list(&self, nur_aktive: bool) -> DbResult<Vec<BenutzerRecord>> {
+        weiterleiten!(self, UserRepository::list, nur_aktive)
+    }
+
+    async fn authenticate(
+        &self,
+        username: &str,
+        password_hash: &str,
+    ) -> DbResult<Option<BenutzerRecord>> {
+        weiterleiten!(self, UserRepository::authenticate, username, password_hash)
+    }
+
+    async fn update_last_login(&self, id: Uuid) -> DbResult<()> {
+        weiterleiten!(self, UserRepository::update_last_login, id)
+    }
+}
+
+impl ChannelRepository for Datenbank {
+    async fn create(&self, data: NeuerKanal<'_>) -> DbResult<KanalRecord> {
+        weiterleiten!(self, ChannelRepository::create, data)
+    }
+
+    async fn get_by_id(&self, id: Uuid) -> DbResult<Option<KanalRecord>> {
+        weiterleiten!(self, ChannelRepository::get_by_id, id)
+    }
+
+    async fn list(&self) -> DbResult<Vec<KanalRecord>> {
+        weiterleiten!(self, ChannelRepository::list)
+    }
+
+    async fn list_filtered(&self, filter: KanalFilter) -> DbResult<Vec<KanalRecord>> {
+        weiterleiten!(self, ChannelRepository::list_filtered, filter)
+    }
+
+    async fn count_filtered(&self, filter: KanalFilter) -> DbResult<i64> {
+        weiterleiten!(self, ChannelRepository::count_filtered, filter)
+    }
+
+    async fn update(&self, id: Uuid, data: KanalUpdate) -> DbResult<KanalRecord> {
+        weiterleiten!(self, ChannelRepository::update, id, data)
+    }
+
+    async fn delete(&self, id: Uuid) -> DbResult<bool> {
+        weiterleiten!(self, ChannelRepository::delete, id)
+    }
+
+    async fn get_children(&self, parent_id: Uuid) -> DbResult<Vec<KanalRecord>> {
+        weiterleiten!(self, ChannelRepository::get_children, parent_id)
+    }
+
+    async fn get_default(&self) -> DbResult<Option<KanalRecord>> {
+        weiterleiten!(self, ChannelRepository::get_default)
+    }
+}
+
+impl PermissionRepository for Datenbank {
+    async fn get_permissions(
+        &self,
+        ziel: &BerechtigungsZiel,
+        channel_id: Option<Uuid>,
+    ) -> DbResult<Vec<(String, BerechtigungsWert)>> {
+        weiterleiten!(
+            self,
+            PermissionRepository::get_permissions,
+            ziel,
+            channel_id
+        )
+    }
+
+    async fn set_permission(
+        &self,
+        ziel: &BerechtigungsZiel,
+        permission_key: &str,
+        wert: BerechtigungsWert,
+        channel_id: Option<Uuid>,
+    ) -> DbResult<()> {
+        weiterleiten!(
+            self,
+            PermissionRepository::set_permission,
+            ziel,
+            permission_key,
+            wert,
+            channel_id
+        )
+    }
+
+    async fn remove_permission(
+        &self,
+        ziel: &BerechtigungsZiel,
+        permission_key: &str,
+        channel_id: Option<Uuid>,
+    ) -> DbResult<bool> {
+        weiterleiten!(
+            self,
+            PermissionRepository::remove_permission,
+            ziel,
+            permission_key,
+            channel_id
+        )
+    }
+
+    async fn resolve_effective_permissions(
+        &self,
+        user_id: Uuid,
+        channel_id: Uuid,
+    ) -> DbResult<Vec<EffektiveBerechtigung>> {
+        weiterleiten!(
+            self,
+            PermissionRepository::resolve_effective_permissions,
+            user_id,
+            channel_id
+        )
+    }
+
+    async fn resolve_with_trace(
+        &self,
+        user_id: Uuid,
+        channel_id: Uuid,
+    ) -> DbResult<Vec<BerechtigungsSpur>> {
+        weiterleiten!(
+            self,
+            PermissionRepository::resolve_with_trace,
+            user_id,
+            channel_id
+        )
+    }
+}
+
+impl ServerGroupRepository for Datenbank {
+    async fn create(&self, data: NeueServerGruppe<'_>) -> DbResult<ServerGruppeRecord> {
+        weiterleiten!(self, ServerGroupRepository::create, data)
+    }
+
+    async fn get(&self, id: Uuid) -> DbResult<Option<ServerGruppeRecord>> {
+        weiterleiten!(self, ServerGroupRepository::get, id)
+    }
+
+    async fn list(&self) -> DbResult<Vec<ServerGruppeRecord>> {
+        weiterleiten!(self, ServerGroupRepository::list)
+    }
+
+    async fn list_for_user(&self, user_id: Uuid) -> DbResult<Vec<ServerGruppeRecord>> {
+        weiterleiten!(self, ServerGroupRepository::list_for_user, user_id)
+    }
+
+    async fn add_member(&self, group_id: Uuid, user_id: Uuid) -> DbResult<()> {
+        weiterleiten!(self, ServerGroupRepository::add_member, group_id, user_id)
+    }
+
+    async fn remove_member(&self, group_id: Uuid, user_id: Uuid) -> DbResult<bool> {
+        weiterleiten!(
+            self,
+            ServerGroupRepository::remove_member,
+            group_id,
+            user_id
+        )
+    }
+
+    async fn get_default(&self) -> DbResult<Option<ServerGruppeRecord>> {
+        weiterleiten!(self, ServerGroupRepository::get_default)
+    }
+
+    async fn delete(&self, id: Uuid) -> DbResult<bool> {
+        weiterleiten!(self, ServerGroupRepository::delete, id)
+    }
+}
+
+impl ChannelGroupRepository for Datenbank {
+    async fn create(&self, data: NeueKanalGruppe<'_>) -> DbResult<KanalGruppeRecord> {
+        weiterleiten!(self, ChannelGroupRepository::create, data)
+    }
+
+    async fn get(&self, id: Uuid) -> DbResult<Option<KanalGruppeRecord>> {
+        weiterleiten!(self, ChannelGroupRepository::get, id)
+    }
+
+    async fn list(&self) -> DbResult<Vec<KanalGruppeRecord>> {
+        weiterleiten!(self, ChannelGroupRepository::list)
+    }
+
+    async fn get_for_user_in_channel(
+        &self,
+        user_id: Uuid,
+        channel_id: Uuid,
+    ) -> DbResult<Option<KanalGruppeRecord>> {
+        weiterleiten!(
+            self,
+            ChannelGroupRepository::get_for_user_in_channel,
+            user_id,
+            channel_id
+        )
+    }
+
+    async fn set_member_group(
+        &self,
+        user_id: Uuid,
+        channel_id: Uuid,
+        group_id: Uuid,
+    ) -> DbResult<()> {
+        weiterleiten!(
+            self,
+            ChannelGroupRepository::set_member_group,
+            user_id,
+            channel_id,
+            group_id
+        )
+    }
+
+    async fn remove_member_group(&self, user_id: Uuid, channel_id: Uuid) -> DbResult<bool> {
+        weiterleiten!(
+            self,
+            ChannelGroupRepository::remove_member_group,
+            user_id,
+            channel_id
+        )
+    }
+
+    async fn delete(&self, id: Uuid) -> DbResult<bool> {
+        weiterleiten!(self, ChannelGroupRepository::delete, id)
+    }
+}
+
+impl BanRepository for Datenbank {
+    async fn create(&self, data: NeuerBan<'_>) -> DbResult<BanRecord> {
+        weiterleiten!(self, BanRepository::create, data)
+    }
+
+    async fn get(&self, id: Uuid) -> DbResult<Option<BanRecord>> {
+        weiterleiten!(self, BanRepository::get, id)
+    }
+
+    async fn list(&self, nur_aktive: bool) -> DbResult<Vec<BanRecord>> {
+        weiterleiten!(self, BanRepository::list, nur_aktive)
+    }
+
+    async fn remove(&self, id: Uuid) -> DbResult<bool> {
+        weiterleiten!(self, BanRepository::remove, id)
+    }
+
+    async fn is_banned(
+        &self,
+        user_id: Option<Uuid>,
+        ip: Option<&str>,
+    ) -> DbResult<Option<BanRecord>> {
+        weiterleiten!(self, BanRepository::is_banned, user_id, ip)
+    }
+
+    async fn cleanup_expired(&self) -> DbResult<u64> {
+        weiterleiten!(self, BanRepository::cleanup_expired)
+    }
+}
+
+impl AuditLogRepository for Datenbank {
+    async fn log_event(
+        &self,
+        actor_id: Option<Uuid>,
+        action: &str,
+        target_type: Option<&str>,
+        target_id: Option<&str>,
+        details: serde_json::Value,
+    ) -> DbResult<AuditLogRecord> {
+        weiterleiten!(
+            self,
+            AuditLogRepository::log_event,
+            actor_id,
+            action,
+            target_type,
+            target_id,
+            details
+        )
+    }
+
+    async fn log_events(&self, eintraege: &[NeuerAuditEintrag]) -> DbResult<()> {
+        weiterleiten!(self, AuditLogRepository::log_events, eintraege)
+    }
+
+    async fn list_events(&self, filter: AuditLogFilter) -> DbResult<Vec<AuditLogRecord>> {
+        weiterleiten!(self, AuditLogRepository::list_events, filter)
+    }
+
+    async fn count_events(&self, filter: AuditLogFilter) -> DbResult<i64> {
+        weiterleiten!(self, AuditLogRepository::count_events, filter)
+    }
+}
+
+impl InviteRepository for Datenbank {
+    async fn create(&self, data: NeueEinladung<'_>) -> DbResult<EinladungRecord> {
+        weiterleiten!(self, InviteRepository::create, data)
+    }
+
+    async fn get(&self, id: Uuid) -> DbResult<Option<EinladungRecord>> {
+        weiterleiten!(self, InviteRepository::get, id)
+    }
+
+    async fn get_by_code(&self, code: &str) -> DbResult<Option<EinladungRecord>> {
+        weiterleiten!(self, InviteRepository::get_by_code, code)
+    }
+
+    async fn list(&self, created_by: Option<Uuid>) -> DbResult<Vec<EinladungRecord>> {
+        weiterleiten!(self, InviteRepository::list, created_by)
+    }
+
+    async fn use_invite(&self, code: &str) -> DbResult<Option<EinladungRecord>> {
+        weiterleiten!(self, InviteRepository::use_invite, code)
+    }
+
+    async fn release_invite(&self, code: &str) -> DbResult<bool> {
+        weiterleiten!(self, InviteRepository::release_invite, code)
+    }
+
+    async fn revoke(&self, id: Uuid) -> DbResult<bool> {
+        weiterleiten!(self, InviteRepository::revoke, id)
+    }
+}
+
+impl ChatMessageRepository for Datenbank {
+    async fn create(&self, data: NeueNachricht<'_>) -> DbResult<ChatNachrichtRecord> {
+        weiterleiten!(self, ChatMessageRepository::create, data)
+    }
+
+    async fn get_by_id(&self, id: Uuid) -> DbResult<Option<ChatNachrichtRecord>> {
+        weiterleiten!(self, ChatMessageRepository::get_by_id, id)
+    }
+
+    async fn get_history(&self, filter: NachrichtenFilter) -> DbResult<Vec<ChatNachrichtRecord>> {
+        weiterleiten!(self, ChatMessageRepository::get_history, filter)
+    }
+
+    async fn update_content(&self, id: Uuid, new_content: &str) -> DbResult<ChatNachrichtRecord> {
+        weiterleiten!(self, ChatMessageRepository::update_content, id, new_content)
+    }
+
+    async fn soft_delete(&self, id: Uuid) -> DbResult<bool> {
+        weiterleiten!(self, ChatMessageRepository::soft_delete, id)
+    }
+
+    async fn search(&self, suche: NachrichtenSuche<'_>) -> DbResult<Vec<ChatNachrichtRecord>> {
+        weiterleiten!(self, ChatMessageRepository::search, suche)
+    }
+}
+
+impl FileRepository for Datenbank {
+    async fn create(&self, data: NeueDatei<'_>) -> DbResult<DateiRecord> {
+        weiterleiten!(self, FileRepository::create, data)
+    }
+
+    async fn get_by_id(&self, id: Uuid) -> DbResult<Option<DateiRecord>> {
+        weiterleiten!(self, FileRepository::get_by_id, id)
+    }
+
+    async fn list_by_channel(&self, channel_id: Uuid) -> DbResult<Vec<DateiRecord>> {
+        weiterleiten!(self, FileRepository::list_by_channel, channel_id)
+    }
+
+    async fn soft_delete(&self, id: Uuid) -> DbResult<bool> {
+        weiterleiten!(self, FileRepository::soft_delete, id)
+    }
+
+    async fn get_quota(&self, group_id: &str) -> DbResult<DateiKontingentRecord> {
+        weiterleiten!(self, FileRepository::get_quota, group_id)
+    }
+
+    async fn increment_usage(&self, group_id: &str, bytes: i64) -> DbResult<()> {
+        weiterleiten!(self, FileRepository::increment_usage, group_id, bytes)
+    }
+
+    async fn decrement_usage(&self, group_id: &str, bytes: i64) -> DbResult<()> {
+        weiterleiten!(self, FileRepository::decrement_usage, group_id, bytes)
+    }
+
+    async fn log_access(&self, data: NeuerDateiZugriff) -> DbResult<DateiZugriffRecord> {
+        weiterleiten!(self, FileRepository::log_access, data)
+    }
+
+    async fn list_access(&self, filter: DateiZugriffFilter) -> DbResult<Vec<DateiZugriffRecord>> {
+        weiterleiten!(self, FileRepository::list_access, filter)
+    }
+
+    async fn count_access(&self, filter: DateiZugriffFilter) -> DbResult<i64> {
+        weiterleiten!(self, FileRepository::count_access, filter)
+    }
+
+    async fn count_access_by_file(&self, file_ids: &[Uuid]) -> DbResult<Vec<(Uuid, i64)>> {
+        weiterleiten!(self, FileRepository::count_access_by_file, file_ids)
+    }
+
+    async fn purge_access_before(&self, before: DateTime<Utc>) -> DbResult<u64> {
+        weiterleiten!(self, FileRepository::purge_access_before, before)
+    }
+
+    async fn create_upload(&self, data: NeuerUpload<'_>) -> DbResult<UploadRecord> {
+        weiterleiten!(self, FileRepository::create_upload, data)
+    }
+
+    async fn get_upload(&self, id: Uuid) -> DbResult<Option<UploadRecord>> {
+        weiterleiten!(self, FileRepository::get_upload, id)
+    }
+
+    async fn complete_upload(
+        &self,
+        id: Uuid,
+        storage_path: &str,
+        checksum: &str,
+    ) -> DbResult<Option<DateiRecord>> {
+        weiterleiten!(
+            self,
+            FileRepository::complete_upload,
+            id,
+            storage_path,
+            checksum
+        )
+    }
+
+    async fn purge_uploads_before(&self, before: DateTime<Utc>) -> DbResult<u64> {
+        weiterleiten!(self, FileRepository::purge_uploads_before, before)
+    }
+}
+
+impl SoundboardRepository for Datenbank {
+    async fn create(&self, data: NeuerSound<'_>) -> DbResult<SoundRecord> {
+        weiterleiten!(self, SoundboardRepository::create, data)
+    }
+
+    async fn get_by_id(&self, id: Uuid) -> DbResult<Option<SoundRecord>> {
+        weiterleiten!(self, SoundboardRepository::get_by_id, id)
+    }
+
+    async fn list(&self, channel_id: Option<Uuid>) -> DbResult<Vec<SoundRecord>> {
+        weiterleiten!(self, SoundboardRepository::list, channel_id)
+    }
+
+    async fn get_frames(&self, id: Uuid) -> DbResult<Option<Vec<Vec<u8>>>> {
+        weiterleiten!(self, SoundboardRepository::get_frames, id)
+    }
+
+    async fn delete(&self, id: Uuid) -> DbResult<bool> {
+        weiterleiten!(self, SoundboardRepository::delete, id)
+    }
+}
+
+impl ChannelTemplateRepository for Datenbank {
+    async fn create(&self, data: NeueKanalVorlage<'_>) -> DbResult<KanalVorlageRecord> {
+        weiterleiten!(self, ChannelTemplateRepository::create, data)
+    }
+
+    async fn get_by_id(&self, id: Uuid) -> DbResult<Option<KanalVorlageRecord>> {
+        weiterleiten!(self, ChannelTemplateRepository::get_by_id, id)
+    }
+
+    async fn list(&self) -> DbResult<Vec<KanalVorlageRecord>> {
+        weiterleiten!(self, ChannelTemplateRepository::list)
+    }
+
+    async fn delete(&self, id: Uuid) -> DbResult<bool> {
+        weiterleiten!(self, ChannelTemplateRepository::delete, id)
+    }
+
+    async fn instantiate(
+        &self,
+        vorlage_id: Uuid,
+        parent_id: Option<Uuid>,
+        name_prefix: Option<&str>,
+        grenzen: KanalbaumGrenzen,
+    ) -> DbResult<Vec<KanalRecord>> {
+        weiterleiten!(
+            self,
+            ChannelTemplateRepository::instantiate,
+            vorlage_id,
+            parent_id,
+            name_prefix,
+            grenzen
+        )
+    }
+}
+
+impl ImportRepository for Datenbank {
+    async fn kanaele_importieren(
+        &self,
+        wurzeln: &[VorlagenKnoten],
+        grenzen: KanalbaumGrenzen,
+    ) -> DbResult<Vec<KanalRecord>> {
+        weiterleiten!(
+            self,
+            ImportRepository::kanaele_importieren,
+            wurzeln,
+            grenzen
+        )
+    }
+
+    async fn server_gruppen_importieren(
+        &self,
+        gruppen: &[ImportServerGruppe],
+    ) -> DbResult<Vec<ServerGruppeRecord>> {
+        weiterleiten!(self, ImportRepository::server_gruppen_importieren, gruppen)
+    }
+
+    async fn benutzer_importieren(
+        &self,
+        benutzer: &[ImportBenutzer],
+    ) -> DbResult<Vec<BenutzerRecord>> {
+        weiterleiten!(self, ImportRepository::benutzer_importieren, benutzer)
+    }
+
+    async fn einladungen_importieren(
+        &self,
+        einladungen: &[NeueEinladung<'_>],
+    ) -> DbResult<Vec<EinladungRecord>> {
+        weiterleiten!(self, ImportRepository::einladungen_importieren, einladungen)
+    }
+}
+
+impl SettingsRepository for Datenbank {
+    async fn get(&self, key: &str) -> DbResult<Option<String>> {
+        weiterleiten!(self, SettingsRepository::get, key)
+    }
+
+    async fn set(&self, key: &str, value: &str) -> DbResult<()> {
+        weiterleiten!(self, SettingsRepository::set, key, value)
+    }
+
+    async fn set_default(&self, key: &str, value: &str) -> DbResult<bool> {
+        weiterleiten!(self, SettingsRepository::set_default, key, value)
+    }
+
+    async fn list(&self) -> DbResult<Vec<EinstellungRecord>> {
+        weiterleiten!(self, SettingsRepository::list)
+    }
+}
+
+impl ServerStartRepository for Datenbank {
+    async fn create(&self, data: NeuerServerStart<'_>) -> DbResult<ServerStartRecord> {
+        weiterleiten!(self, ServerStartRepository::create, data)
+    }
+
+    async fn list_recent(&self, limit: i64) -> DbResult<Vec<ServerStartRecord>> {
+        weiterleiten!(self, ServerStartRepository::list_recent, limit)
+    }
+}
+
+impl ZeitplanRepository for Datenbank {
+    async fn create(&self, data: NeueGeplanteAktion<'_>) -> DbResult<GeplanteAktionRecord> {
+        weiterleiten!(self, ZeitplanRepository::create, data)
+    }
+
+    async fn get_by_id(&self, id: Uuid) -> DbResult<Option<GeplanteAktionRecord>> {
+        weiterleiten!(self, ZeitplanRepository::get_by_id, id)
+    }
+
+    async fn list(&self) -> DbResult<Vec<GeplanteAktionRecord>> {
+        weiterleiten!(self, ZeitplanRepository::list)
+    }
+
+    async fn cancel(&self, id: Uuid) -> DbResult<bool> {
+        weiterleiten!(self, ZeitplanRepository::cancel, id)
+    }
+
+    async fn due(&self, jetzt: DateTime<Utc>) -> DbResult<Vec<GeplanteAktionRecord>> {
+        weiterleiten!(self, ZeitplanRepository::due, jetzt)
+    }
+
+    async fn claim(
+        &self,
+        id: Uuid,
+        geplant: DateTime<Utc>,
+        naechste: Option<DateTime<Utc>>,
+        jetzt: DateTime<Utc>,
+    ) -> DbResult<bool> {
+        weiterleiten!(
+            self,
+            ZeitplanRepository::claim,
+            id,
+            geplant,
+            naechste,
+            jetzt
+        )
+    }
+}
+
+impl KontoRepository for Datenbank {
+    async fn daten_sammeln(&self, user_id: Uuid) -> DbResult<Option<KontoDaten>> {
+        weiterleiten!(self, KontoRepository::daten_sammeln, user_id)
+    }
+
+    async fn export_anlegen(
+        &self,
+        data: NeuerKontoExport<'_>,
+    ) -> DbResult<Option<KontoExportRecord>> {
+        weiterleiten!(self, KontoRepository::export_anlegen, data)
+    }
+
+    async fn export_abschliessen(&self, id: Uuid, size_bytes: i64) -> DbResult<()> {
+        weiterleiten!(self, KontoRepository::export_abschliessen, id, size_bytes)
+    }
+
+    async fn export_entfernen(&self, id: Uuid) -> DbResult<()> {
+        weiterleiten!(self, KontoRepository::export_entfernen, id)
+    }
+
+    async fn export_einloesen(
+        &self,
+        token_hash: &str,
+        jetzt: DateTime<Utc>,
+    ) -> DbResult<Option<KontoExportRecord>> {
+        weiterleiten!(self, KontoRepository::export_einloesen, token_hash, jetzt)
+    }
+
+    async fn exporte_ablaufen(&self, jetzt: DateTime<Utc>) -> DbResult<Vec<KontoExportRecord>> {
+        weiterleiten!(self, KontoRepository::exporte_ablaufen, jetzt)
+    }
+
+    async fn konto_loeschen(&self, auftrag: KontoLoeschAuftrag<'_>) -> DbResult<KontoLoeschung> {
+        weiterleiten!(self, KontoRepository::konto_loeschen, auftrag)
+    }
+
+    async fn bereinigung_anlegen(
+        &self,
+        data: NeueInhaltsBereinigung,
+    ) -> DbResult<InhaltsBereinigungRecord> {
+        weiterleiten!(self, KontoRepository::bereinigung_anlegen, data)
+    }
+
+    async fn bereinigung_laden(&self, id: Uuid) -> DbResult<Option<InhaltsBereinigungRecord>> {
+        weiterleiten!(self, KontoRepository::bereinigung_laden, id)
+    }
+
+    async fn bereinigung_stapel(
+        &self,
+        id: Uuid,
+        limit: i64,
+        kontingent_gruppe: &str,
+    ) -> DbResult<InhaltsStapel> {
+        weiterleiten!(
+            self,
+            KontoRepository::bereinigung_stapel,
+            id,
+            limit,
+            kontingent_gruppe
+        )
+    }
+
+    async fn bereinigung_abschliessen(&self, id: Uuid) -> DbResult<InhaltsBereinigungRecord> {
+        weiterleiten!(self, KontoRepository::bereinigung_abschliessen, id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn sqlite_backend_wird_weitergeleitet() {
+        let db = Datenbank::from(SqliteDb::in_memory().await.unwrap());
+        assert_eq!(db.backend(), DatabaseBackend::Sqlite);
+        assert!(db.als_sqlite().is_some());
+        assert!(db.erreichbar().await);
+
+        let benutzer = UserRepository::create(
+            &db,
+            NeuerBenutzer {
+                username: "weiterleitung",
+                password_hash: "hash",
+            },
+        )
+        .await
+        .unwrap();
+        let geladen = UserRepository::get_by_name(&db, "weiterleitung")
+            .await
+            .unwrap()
+            .expect("Benutzer vorhanden");
+        assert_eq!(geladen.id, benutzer.id);
+        assert_eq!(
+            db.schema_version().await.unwrap(),
+            SqliteDb::schema_version_binary()
+        );
+    }
+}
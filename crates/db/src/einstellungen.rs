@@ -0,0 +1,257 @@
+//! Laufzeit-Einstellungen des Servers
+//!
+//! Name, Willkommensnachricht, Client-Limit, Host-Nachricht und die
+//! Standard-Bearbeitungsfrist fuer Chat-Nachrichten liegen in der Tabelle
+//! `server_settings` und koennen per Commander geaendert werden. Beim
+//! ersten Start werden fehlende Schluessel aus der Konfigurationsdatei
+//! vorbelegt ([`ServerEinstellungen::vorbelegen`]); danach ist die Datenbank
+//! massgeblich.
+//!
+//! [`EinstellungsCache`] haelt eine Kopie im Speicher, die bei jeder Aenderung
+//! ueber den Cache verworfen wird. Die Nachricht des Tages liegt in derselben
+//! Tabelle, hat aber eine eigene Versionierung ([`crate::motd`]).
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::DbError;
+use crate::models::MAX_BEARBEITUNGSFRIST_SEK;
+use crate::motd::Motd;
+use crate::repository::{DbResult, SettingsRepository};
+
+/// Schluessel in `server_settings`
+pub mod schluessel {
+    pub const NAME: &str = "server.name";
+    pub const WILLKOMMENSNACHRICHT: &str = "server.welcome_message";
+    pub const MAX_CLIENTS: &str = "server.max_clients";
+    pub const HOST_NACHRICHT: &str = "server.host_message";
+    pub const MOTD: &str = "server.motd";
+    pub const MOTD_VERSION: &str = "server.motd_version";
+    pub const CHAT_BEARBEITUNGSFRIST: &str = "chat.edit_window_secs";
+}
+
+/// Typisierte Sicht auf die Server-Einstellungen
+///
+/// Optionale Texte werden als leerer String gespeichert.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ServerEinstellungen {
+    pub name: String,
+    pub willkommensnachricht: Option<String>,
+    pub max_clients: u32,
+    pub host_nachricht: Option<String>,
+    /// Frist in Sekunden, in der Verfasser ihre Chat-Nachrichten editieren
+    /// und loeschen duerfen (0 = unbegrenzt); Kanaele koennen abweichen
+    #[serde(default)]
+    pub chat_bearbeitungsfrist_sek: u32,
+}
+
+impl ServerEinstellungen {
+    /// Laedt die Einstellungen; fehlende Schluessel nehmen den Wert aus `standard`
+    pub async fn laden<S: SettingsRepository + ?Sized>(
+        repo: &S,
+        standard: &Self,
+    ) -> DbResult<Self> {
+        let optional = |wert: String| Some(wert).filter(|w| !w.is_empty());
+        Ok(Self {
+            name: repo.get_string(schluessel::NAME, &standard.name).await?,
+            willkommensnachricht: optional(
+                repo.get_string(
+                    schluessel::WILLKOMMENSNACHRICHT,
+                    standard.willkommensnachricht.as_deref().unwrap_or_default(),
+                )
+                .await?,
+            ),
+            max_clients: repo
+                .get_u32(schluessel::MAX_CLIENTS, standard.max_clients)
+                .await?,
+            host_nachricht: optional(
+                repo.get_string(
+                    schluessel::HOST_NACHRICHT,
+                    standard.host_nachricht.as_deref().unwrap_or_default(),
+                )
+                .await?,
+            ),
+            chat_bearbeitungsfrist_sek: repo
+                .get_u32(
+                    schluessel::CHAT_BEARBEITUNGSFRIST,
+                    standard.chat_bearbeitungsfrist_sek,
+                )
+                .await?,
+        })
+    }
+
+    /// Legt fehlende Schluessel mit diesen Werten an (erster Start)
+    ///
+    /// Bereits gespeicherte Werte bleiben unveraendert. Gibt die Anzahl der
+    /// neu angelegten Schluessel zurueck.
+    pub async fn vorbelegen<S: SettingsRepository + ?Sized>(&self, repo: &S) -> DbResult<usize> {
+        let max_clients = self.max_clients.to_string();
+        let bearbeitungsfrist = self.chat_bearbeitungsfrist_sek.to_string();
+        let eintraege = [
+            (schluessel::NAME, self.name.as_str()),
+            (
+                schluessel::WILLKOMMENSNACHRICHT,
+                self.willkommensnachricht.as_deref().unwrap_or_default(),
+            ),
+            (schluessel::MAX_CLIENTS, max_clients.as_str()),
+            (
+                schluessel::HOST_NACHRICHT,
+                self.host_nachricht.as_deref().unwrap_or_default(),
+            ),
+            (
+                schluessel::CHAT_BEARBEITUNGSFRIST,
+                bearbeitungsfrist.as_str(),
+            ),
+        ];
+
+        let mut angelegt = 0;
+        for (key, value) in eintraege {
+            if repo.set_default(key, value).await? {
+                angelegt += 1;
+            }
+        }
+        Ok(angelegt)
+    }
+}
+
+/// Aenderung an den Server-Einstellungen (`None` = unveraendert)
+///
+/// Leere Willkommens- und Host-Nachrichten entfernen den Text.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EinstellungsAenderung {
+    pub name: Option<String>,
+    pub willkommensnachricht: Option<String>,
+    pub max_clients: Option<u32>,
+    pub host_nachricht: Option<String>,
+    pub chat_bearbeitungsfrist_sek: Option<u32>,
+}
+
+impl EinstellungsAenderung {
+    /// Gibt `true` zurueck wenn nichts geaendert wird
+    pub fn ist_leer(&self) -> bool {
+        self == &Self::default()
+    }
+
+    /// Prueft die neuen Werte
+    pub fn pruefen(&self) -> DbResult<()> {
+        if self.name.as_deref().is_some_and(|n| n.trim().is_empty()) {
+            return Err(DbError::UngueltigeDaten(
+                "Servername darf nicht leer sein".into(),
+            ));
+        }
+        if self.max_clients == Some(0) {
+            return Err(DbError::UngueltigeDaten(
+                "max_clients muss groesser als 0 sein".into(),
+            ));
+        }
+        if self
+            .chat_bearbeitungsfrist_sek
+            .is_some_and(|s| s > MAX_BEARBEITUNGSFRIST_SEK)
+        {
+            return Err(DbError::UngueltigeDaten(format!(
+                "Bearbeitungsfrist hoechstens {MAX_BEARBEITUNGSFRIST_SEK} Sekunden"
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Zwischengespeicherte Server-Einstellungen
+///
+/// Aenderungen muessen ueber [`EinstellungsCache::aendern`] laufen, damit die
+/// Kopie verworfen wird.
+pub struct EinstellungsCache<S: SettingsRepository> {
+    repo: Arc<S>,
+    standard: ServerEinstellungen,
+    kopie: RwLock<Option<ServerEinstellungen>>,
+    /// Wird bei jeder Invalidierung erhoeht, damit ein parallel laufendes
+    /// Laden keine veraltete Kopie zurueckschreibt
+    generation: AtomicU64,
+    /// Serialisiert MOTD-Aenderungen (Vergleich und Versionssprung)
+    motd_sperre: tokio::sync::Mutex<()>,
+}
+
+impl<S: SettingsRepository> EinstellungsCache<S> {
+    /// Erstellt einen leeren Cache; `standard` gilt fuer fehlende Schluessel
+    pub fn neu(repo: Arc<S>, standard: ServerEinstellungen) -> Self {
+        Self {
+            repo,
+            standard,
+            kopie: RwLock::new(None),
+            generation: AtomicU64::new(0),
+            motd_sperre: tokio::sync::Mutex::new(()),
+        }
+    }
+
+    /// Aktuelle Einstellungen (aus dem Cache oder frisch geladen)
+    pub async fn aktuell(&self) -> DbResult<ServerEinstellungen> {
+        if let Some(kopie) = self.kopie.read().unwrap_or_else(|e| e.into_inner()).clone() {
+            return Ok(kopie);
+        }
+
+        let generation = self.generation.load(Ordering::Acquire);
+        let geladen = ServerEinstellungen::laden(self.repo.as_ref(), &self.standard).await?;
+        let mut kopie = self.kopie.write().unwrap_or_else(|e| e.into_inner());
+        if self.generation.load(Ordering::Acquire) == generation {
+            *kopie = Some(geladen.clone());
+        }
+        Ok(geladen)
+    }
+
+    /// Speichert eine Aenderung und gibt die neuen Einstellungen zurueck
+    pub async fn aendern(
+        &self,
+        aenderung: &EinstellungsAenderung,
+    ) -> DbResult<ServerEinstellungen> {
+        aenderung.pruefen()?;
+
+        let max_clients = aenderung.max_clients.map(|m| m.to_string());
+        let bearbeitungsfrist = aenderung.chat_bearbeitungsfrist_sek.map(|s| s.to_string());
+        let werte = [
+            (schluessel::NAME, aenderung.name.as_deref().map(str::trim)),
+            (
+                schluessel::WILLKOMMENSNACHRICHT,
+                aenderung.willkommensnachricht.as_deref(),
+            ),
+            (schluessel::MAX_CLIENTS, max_clients.as_deref()),
+            (
+                schluessel::HOST_NACHRICHT,
+                aenderung.host_nachricht.as_deref(),
+            ),
+            (
+                schluessel::CHAT_BEARBEITUNGSFRIST,
+                bearbeitungsfrist.as_deref(),
+            ),
+        ];
+        // Vorher verwerfen: auch bei einem Fehler mittendrin ist die Kopie veraltet
+        self.invalidieren();
+        for (key, value) in werte {
+            if let Some(value) = value {
+                self.repo.set(key, value).await?;
+            }
+        }
+        self.invalidieren();
+
+        self.aktuell().await
+    }
+
+    /// Aktuelle Nachricht des Tages
+    pub async fn motd(&self) -> DbResult<Motd> {
+        Motd::laden(self.repo.as_ref()).await
+    }
+
+    /// Speichert eine neue Nachricht des Tages (siehe [`Motd::setzen`])
+    pub async fn motd_setzen(&self, markdown: &str) -> DbResult<(Motd, bool)> {
+        let _sperre = self.motd_sperre.lock().await;
+        Motd::setzen(self.repo.as_ref(), markdown).await
+    }
+
+    /// Verwirft die zwischengespeicherte Kopie
+    pub fn invalidieren(&self) {
+        let mut kopie = self.kopie.write().unwrap_or_else(|e| e.into_inner());
+        self.generation.fetch_add(1, Ordering::AcqRel);
+        *kopie = None;
+    }
+}
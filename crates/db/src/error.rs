@@ -1,7 +1,10 @@
 //! Fehlertypen fuer das Datenbank-Crate
 
+use speakeasy_core::SpeakeasyError;
 use thiserror::Error;
 
+use crate::zeitlimit::Zeitueberschreitung;
+
 /// Datenbank-Fehlertypen
 #[derive(Debug, Error)]
 pub enum DbError {
@@ -51,3 +54,96 @@ impl DbError {
             })
     }
 }
+
+impl From<Zeitueberschreitung> for SpeakeasyError {
+    fn from(e: Zeitueberschreitung) -> Self {
+        Self::Zeitlimit(e.to_string())
+    }
+}
+
+impl From<DbError> for SpeakeasyError {
+    fn from(e: DbError) -> Self {
+        let eindeutigkeit = e.ist_eindeutigkeit();
+        match e {
+            DbError::NichtGefunden(m) => Self::NichtGefunden(m),
+            DbError::Eindeutigkeit(m) => Self::Konflikt(m),
+            DbError::UngueltigeDaten(m) => Self::UngueltigeEingabe(m),
+            DbError::EinladungUngueltig | DbError::EinladungErschoepft => {
+                Self::EinladungUngueltig(e.to_string())
+            }
+            DbError::Sqlx(sqlx::Error::RowNotFound) => Self::NichtGefunden(e.to_string()),
+            DbError::Sqlx(sqlx::Error::PoolTimedOut) => Self::Zeitlimit(e.to_string()),
+            DbError::Sqlx(_) if eindeutigkeit => Self::Konflikt(e.to_string()),
+            DbError::Sqlx(_) | DbError::Migration(_) | DbError::Json(_) | DbError::Intern(_) => {
+                Self::Datenbank(e.to_string())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use speakeasy_core::FehlerCode;
+
+    /// Ein Beispiel je Variante; der `match` ohne Wildcard bricht den Build,
+    /// sobald eine Variante hinzukommt, die hier fehlt
+    fn alle_varianten() -> Vec<DbError> {
+        let beispiele = vec![
+            DbError::NichtGefunden("Kanal".into()),
+            DbError::Eindeutigkeit("name".into()),
+            DbError::UngueltigeDaten("leer".into()),
+            DbError::EinladungUngueltig,
+            DbError::EinladungErschoepft,
+            DbError::Sqlx(sqlx::Error::PoolClosed),
+            DbError::Migration(sqlx::migrate::MigrateError::VersionMissing(1)),
+            DbError::Json(serde_json::from_str::<i32>("x").unwrap_err()),
+            DbError::Intern("kaputt".into()),
+        ];
+        for e in &beispiele {
+            match e {
+                DbError::NichtGefunden(_)
+                | DbError::Eindeutigkeit(_)
+                | DbError::UngueltigeDaten(_)
+                | DbError::EinladungUngueltig
+                | DbError::EinladungErschoepft
+                | DbError::Sqlx(_)
+                | DbError::Migration(_)
+                | DbError::Json(_)
+                | DbError::Intern(_) => {}
+            }
+        }
+        beispiele
+    }
+
+    #[test]
+    fn jede_variante_hat_einen_fachlichen_code() {
+        for e in alle_varianten() {
+            let anzeige = e.to_string();
+            let code = SpeakeasyError::from(e).code();
+            assert_ne!(code, FehlerCode::Intern, "{anzeige}");
+        }
+    }
+
+    #[test]
+    fn spezifische_varianten_bleiben_erhalten() {
+        let code = |e: DbError| SpeakeasyError::from(e).code();
+        assert_eq!(
+            code(DbError::nicht_gefunden("x")),
+            FehlerCode::NichtGefunden
+        );
+        assert_eq!(
+            code(DbError::Eindeutigkeit("x".into())),
+            FehlerCode::Konflikt
+        );
+        assert_eq!(
+            code(DbError::Sqlx(sqlx::Error::RowNotFound)),
+            FehlerCode::NichtGefunden
+        );
+        assert_eq!(
+            code(DbError::EinladungErschoepft),
+            FehlerCode::EinladungUngueltig
+        );
+        assert_eq!(code(DbError::intern("x")), FehlerCode::Datenbank);
+    }
+}
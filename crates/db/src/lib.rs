@@ -2,7 +2,8 @@
 //!
 //! Dieses Crate stellt das Repository-Pattern bereit, das SQLite (Standard)
 //! und PostgreSQL (Multi-Instance) hinter einer einheitlichen Schnittstelle
-//! abstrahiert.
+//! abstrahiert. [`Datenbank`] waehlt das Backend zur Laufzeit anhand von
+//! [`DatabaseConfig::backend`].
 //!
 //! # Verwendung
 //!
@@ -24,17 +25,31 @@
 //! }
 //! ```
 
+pub mod audit_puffer;
+pub mod datenbank;
+pub mod einstellungen;
 pub mod error;
+mod migrationen;
 pub mod models;
+pub mod motd;
+pub mod neustart;
 pub mod permissions;
+pub mod postgres;
 pub mod repository;
 pub mod sqlite;
+pub mod zeitlimit;
+pub mod zeitplan;
 
 // Bequeme Re-Exporte
+pub use audit_puffer::{AuditPuffer, AuditPufferKonfig, AuditSink};
+pub use datenbank::Datenbank;
 pub use error::DbError;
+pub use postgres::PostgresDb;
 pub use repository::{
     AuditLogRepository, BanRepository, ChannelGroupRepository, ChannelRepository,
-    ChatMessageRepository, DatabaseBackend, DatabaseConfig, DbResult, FileRepository,
-    InviteRepository, PermissionRepository, ServerGroupRepository, UserRepository,
+    ChannelTemplateRepository, ChatMessageRepository, DatabaseBackend, DatabaseConfig, DbResult,
+    FileRepository, ImportRepository, InviteRepository, KontoRepository, PermissionRepository,
+    ServerGroupRepository, ServerStartRepository, SettingsRepository, SoundboardRepository,
+    UserRepository, ZeitplanRepository,
 };
-pub use sqlite::SqliteDb;
+pub use sqlite::{MigrationsFortschritt, SqliteDb};
@@ -0,0 +1,112 @@
+//! Schema-Migrationen mit Fortschrittsmeldung (fuer alle Backends)
+
+use sqlx::migrate::{Migrate, MigrateError, Migrator};
+use std::collections::HashMap;
+use tracing::info;
+
+use crate::error::DbError;
+
+/// Fortschritt beim Anwenden der Schema-Migrationen
+///
+/// Wird vor jeder ausstehenden Migration gemeldet; bereits angewendete
+/// Migrationen zaehlen nicht mit.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MigrationsFortschritt {
+    /// Laufende Nummer der Migration (ab 1)
+    pub aktuell: usize,
+    /// Anzahl ausstehender Migrationen
+    pub gesamt: usize,
+    /// Schema-Version der Migration
+    pub version: i64,
+    pub beschreibung: String,
+}
+
+/// Wendet alle ausstehenden Migrationen an und meldet jede vor dem Start
+///
+/// Entspricht `Migrator::run`, zaehlt aber vorab die ausstehenden
+/// Migrationen, damit der Fortschritt als "N von M" gemeldet werden kann.
+pub(crate) async fn anwenden<C>(
+    conn: &mut C,
+    migrationen: &Migrator,
+    mut fortschritt: impl FnMut(MigrationsFortschritt),
+) -> Result<(), DbError>
+where
+    C: Migrate + ?Sized,
+{
+    conn.ensure_migrations_table().await?;
+    if let Some(version) = conn.dirty_version().await? {
+        return Err(MigrateError::Dirty(version).into());
+    }
+
+    let angewendet: HashMap<i64, _> = conn
+        .list_applied_migrations()
+        .await?
+        .into_iter()
+        .map(|m| (m.version, m.checksum))
+        .collect();
+    if let Some(version) = angewendet
+        .keys()
+        .find(|version| !migrationen.version_exists(**version))
+    {
+        return Err(MigrateError::VersionMissing(*version).into());
+    }
+
+    let mut ausstehend = Vec::new();
+    for migration in migrationen.iter() {
+        if migration.migration_type.is_down_migration() {
+            continue;
+        }
+        match angewendet.get(&migration.version) {
+            Some(checksum) if *checksum != migration.checksum => {
+                return Err(MigrateError::VersionMismatch(migration.version).into());
+            }
+            Some(_) => {}
+            None => ausstehend.push(migration),
+        }
+    }
+
+    let gesamt = ausstehend.len();
+    for (index, migration) in ausstehend.into_iter().enumerate() {
+        fortschritt(MigrationsFortschritt {
+            aktuell: index + 1,
+            gesamt,
+            version: migration.version,
+            beschreibung: migration.description.to_string(),
+        });
+        let dauer = conn.apply(migration).await?;
+        info!(
+            version = migration.version,
+            beschreibung = %migration.description,
+            dauer_ms = dauer.as_millis() as u64,
+            "Migration angewendet"
+        );
+    }
+
+    info!(angewendet = gesamt, "Datenbank-Migrationen abgeschlossen");
+    Ok(())
+}
+
+/// Hoechste Schema-Version, die ein Satz Migrationen mitbringt
+pub(crate) fn hoechste_version(migrationen: &Migrator) -> i64 {
+    migrationen
+        .iter()
+        .filter(|m| !m.migration_type.is_down_migration())
+        .map(|m| m.version)
+        .max()
+        .unwrap_or(0)
+}
+
+/// Hoechste erfolgreich angewendete Schema-Version einer Datenbank
+pub(crate) async fn angewendete_version<C>(conn: &mut C) -> Result<i64, DbError>
+where
+    C: Migrate + ?Sized,
+{
+    conn.ensure_migrations_table().await?;
+    Ok(conn
+        .list_applied_migrations()
+        .await?
+        .into_iter()
+        .map(|m| m.version)
+        .max()
+        .unwrap_or(0))
+}
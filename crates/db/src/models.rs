@@ -7,6 +7,8 @@ use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+use crate::zeitplan::Zeitplan;
+
 // ---------------------------------------------------------------------------
 // Benutzer
 // ---------------------------------------------------------------------------
@@ -73,6 +75,12 @@ impl std::str::FromStr for KanalTyp {
     }
 }
 
+/// Groesster erlaubter Langsam-Modus eines Kanals in Sekunden (6 Stunden)
+pub const MAX_LANGSAMMODUS_SEK: u32 = 6 * 60 * 60;
+
+/// Groesste einstellbare Bearbeitungsfrist fuer Chat-Nachrichten (7 Tage)
+pub const MAX_BEARBEITUNGSFRIST_SEK: u32 = 7 * 24 * 60 * 60;
+
 /// Kanal-Datensatz aus der Datenbank
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct KanalRecord {
@@ -85,6 +93,19 @@ pub struct KanalRecord {
     pub is_default: bool,
     pub sort_order: i64,
     pub channel_type: KanalTyp,
+    /// Codec-Profil (Name eines Audio-Presets, None = Server-Standard)
+    #[serde(default)]
+    pub codec_profile: Option<String>,
+    /// Langsam-Modus: Sekunden zwischen zwei Chat-Nachrichten (0 = aus)
+    #[serde(default)]
+    pub slow_mode_secs: i64,
+    /// Frist fuer das Editieren und Loeschen eigener Nachrichten in
+    /// Sekunden (None = Server-Standard, 0 = unbegrenzt)
+    #[serde(default)]
+    pub edit_window_secs: Option<i64>,
+    /// Beitritt nur nach Freigabe durch ein Mitglied (Anklopfen)
+    #[serde(default)]
+    pub join_by_approval: bool,
     pub created_at: DateTime<Utc>,
 }
 
@@ -126,6 +147,41 @@ pub struct KanalUpdate {
     pub max_clients: Option<i64>,
     pub is_default: Option<bool>,
     pub sort_order: Option<i64>,
+    pub slow_mode_secs: Option<i64>,
+    /// `Some(None)` setzt die Frist auf den Server-Standard zurueck
+    pub edit_window_secs: Option<Option<i64>>,
+    pub join_by_approval: Option<bool>,
+}
+
+/// Filter fuer seitenweise Kanallisten
+#[derive(Debug, Clone, Default)]
+pub struct KanalFilter {
+    /// Teil des Kanalnamens (Gross-/Kleinschreibung egal)
+    pub name_contains: Option<String>,
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+impl KanalFilter {
+    /// LIKE-Muster fuer `name_contains` (Escape-Zeichen `\`)
+    pub fn name_muster(&self) -> Option<String> {
+        self.name_contains.as_deref().map(like_muster)
+    }
+}
+
+/// LIKE-Muster, das `text` an beliebiger Stelle findet; `\`, `%` und `_`
+/// werden mit `\` maskiert
+pub fn like_muster(text: &str) -> String {
+    let mut muster = String::with_capacity(text.len() + 2);
+    muster.push('%');
+    for c in text.chars() {
+        if matches!(c, '\\' | '%' | '_') {
+            muster.push('\\');
+        }
+        muster.push(c);
+    }
+    muster.push('%');
+    muster
 }
 
 // ---------------------------------------------------------------------------
@@ -210,6 +266,24 @@ impl TriState {
             Self::Deny => Some(0),
         }
     }
+
+    /// Konvertiert aus optionalem Bool (None=Skip, true=Grant, false=Deny)
+    pub fn from_opt_bool(v: Option<bool>) -> Self {
+        match v {
+            None => Self::Skip,
+            Some(true) => Self::Grant,
+            Some(false) => Self::Deny,
+        }
+    }
+
+    /// Konvertiert zu optionalem Bool
+    pub fn to_opt_bool(self) -> Option<bool> {
+        match self {
+            Self::Skip => None,
+            Self::Grant => Some(true),
+            Self::Deny => Some(false),
+        }
+    }
 }
 
 /// Berechtigung-Datensatz
@@ -306,6 +380,39 @@ pub struct AuditLogRecord {
     pub timestamp: DateTime<Utc>,
 }
 
+/// Noch nicht geschriebenes Audit-Ereignis (fuer Sammel-Schreibvorgaenge)
+///
+/// Der Zeitstempel wird beim Erzeugen gesetzt, nicht beim Schreiben, damit
+/// gepuffert geschriebene Ereignisse ihre Reihenfolge behalten.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NeuerAuditEintrag {
+    pub actor_id: Option<Uuid>,
+    pub action: String,
+    pub target_type: Option<String>,
+    pub target_id: Option<String>,
+    pub details: serde_json::Value,
+    pub timestamp: DateTime<Utc>,
+}
+
+impl NeuerAuditEintrag {
+    pub fn neu(
+        actor_id: Option<Uuid>,
+        action: impl Into<String>,
+        target_type: Option<&str>,
+        target_id: Option<&str>,
+        details: serde_json::Value,
+    ) -> Self {
+        Self {
+            actor_id,
+            action: action.into(),
+            target_type: target_type.map(str::to_string),
+            target_id: target_id.map(str::to_string),
+            details,
+            timestamp: Utc::now(),
+        }
+    }
+}
+
 /// Filter fuer Audit-Log-Abfragen
 #[derive(Debug, Clone, Default)]
 pub struct AuditLogFilter {
@@ -395,6 +502,9 @@ pub struct ChatNachrichtRecord {
     pub created_at: DateTime<Utc>,
     pub edited_at: Option<DateTime<Utc>>,
     pub deleted_at: Option<DateTime<Utc>>,
+    /// Anzahl der Bearbeitungen (fuer Moderations-Auswertungen)
+    #[serde(default)]
+    pub edit_count: i64,
 }
 
 /// Daten zum Erstellen einer neuen Chat-Nachricht
@@ -417,6 +527,29 @@ pub struct NachrichtenFilter {
     pub limit: Option<i64>,
 }
 
+/// Textsuche in den Nachrichten eines Kanals (neueste zuerst)
+#[derive(Debug, Clone)]
+pub struct NachrichtenSuche<'a> {
+    pub channel_id: Uuid,
+    /// Suchtext; `%`, `_` und `\` gelten woertlich, nicht als Platzhalter
+    pub query: &'a str,
+    /// Cursor: nur Nachrichten vor diesem Zeitstempel
+    pub before: Option<DateTime<Utc>>,
+    /// Letzter Treffer der Vorseite; zeitgleiche, aeltere Treffer folgen noch
+    pub before_id: Option<Uuid>,
+    /// Maximale Anzahl Treffer
+    pub limit: i64,
+    /// Zu ueberspringende Treffer
+    pub offset: i64,
+}
+
+impl NachrichtenSuche<'_> {
+    /// LIKE-Muster fuer den Suchtext (Escape-Zeichen `\`)
+    pub fn like_muster(&self) -> String {
+        like_muster(self.query)
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Dateien
 // ---------------------------------------------------------------------------
@@ -448,6 +581,35 @@ pub struct NeueDatei<'a> {
     pub checksum: &'a str,
 }
 
+/// Angemeldeter, noch nicht uebertragener Datei-Upload
+///
+/// Die ID wird beim Abschluss zur ID des [`DateiRecord`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UploadRecord {
+    pub id: Uuid,
+    pub channel_id: Uuid,
+    pub uploader_id: Uuid,
+    pub filename: String,
+    pub mime_type: String,
+    pub size_bytes: i64,
+    /// Vom Client angekuendigter SHA-256 (hex)
+    pub checksum: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// Daten zum Anmelden eines Datei-Uploads
+#[derive(Debug, Clone)]
+pub struct NeuerUpload<'a> {
+    pub channel_id: Uuid,
+    pub uploader_id: Uuid,
+    pub filename: &'a str,
+    pub mime_type: &'a str,
+    pub size_bytes: i64,
+    pub checksum: Option<&'a str>,
+    pub expires_at: DateTime<Utc>,
+}
+
 /// Datei-Kontingent-Datensatz
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DateiKontingentRecord {
@@ -456,3 +618,505 @@ pub struct DateiKontingentRecord {
     pub max_total_storage: i64,
     pub current_usage: i64,
 }
+
+/// Eintrag im Datei-Zugriffsprotokoll (append-only)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DateiZugriffRecord {
+    pub id: Uuid,
+    pub file_id: Uuid,
+    /// `None` wenn im Aggregat-Modus protokolliert wurde
+    pub user_id: Option<Uuid>,
+    pub timestamp: DateTime<Utc>,
+    pub bytes_served: i64,
+    pub completed: bool,
+}
+
+/// Daten fuer einen neuen Zugriffsprotokoll-Eintrag
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NeuerDateiZugriff {
+    pub file_id: Uuid,
+    pub user_id: Option<Uuid>,
+    pub bytes_served: i64,
+    pub completed: bool,
+}
+
+/// Filter fuer Abfragen des Datei-Zugriffsprotokolls
+#[derive(Debug, Clone, Default)]
+pub struct DateiZugriffFilter {
+    pub file_id: Option<Uuid>,
+    pub user_id: Option<Uuid>,
+    pub since: Option<DateTime<Utc>>,
+    pub until: Option<DateTime<Utc>>,
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+// ---------------------------------------------------------------------------
+// Soundboard
+// ---------------------------------------------------------------------------
+
+/// Soundboard-Eintrag (ohne die kodierten Frames)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SoundRecord {
+    pub id: Uuid,
+    pub name: String,
+    /// Nur in diesem Kanal abspielbar (`None` = serverweit)
+    pub channel_id: Option<Uuid>,
+    /// Hochgeladene Quelldatei
+    pub file_id: Option<Uuid>,
+    /// Dauer eines Opus-Frames in Millisekunden
+    pub frame_ms: u32,
+    pub duration_ms: u32,
+    pub created_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Daten zum Registrieren eines Sounds
+#[derive(Debug, Clone)]
+pub struct NeuerSound<'a> {
+    pub name: &'a str,
+    pub channel_id: Option<Uuid>,
+    pub file_id: Option<Uuid>,
+    pub frame_ms: u32,
+    /// Vorab kodierte Opus-Frames in Abspielreihenfolge
+    pub frames: &'a [Vec<u8>],
+    pub created_by: Option<Uuid>,
+}
+
+// ---------------------------------------------------------------------------
+// Kanal-Vorlagen
+// ---------------------------------------------------------------------------
+
+/// Erlaubte Codec-Profile (entsprechen `speakeasy_protocol::codec::AudioPreset`)
+pub const CODEC_PROFILE: &[&str] = &["speech", "balanced", "music", "low_bandwidth"];
+
+/// Maximale Laenge eines Kanalnamens in einer Vorlage
+pub const MAX_VORLAGEN_KANALNAME: usize = 64;
+
+fn standard_kanal_typ() -> KanalTyp {
+    KanalTyp::Voice
+}
+
+/// Knoten einer Kanal-Vorlage – beschreibt einen Kanal samt Unterkanaelen
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VorlagenKnoten {
+    pub name: String,
+    #[serde(default = "standard_kanal_typ")]
+    pub channel_type: KanalTyp,
+    #[serde(default)]
+    pub topic: Option<String>,
+    /// 0 = unbegrenzt
+    #[serde(default)]
+    pub max_clients: i64,
+    #[serde(default)]
+    pub codec_profile: Option<String>,
+    /// Kanal-spezifische Berechtigungen und Gruppen-Zuweisungen
+    #[serde(default)]
+    pub permissions: Vec<VorlagenBerechtigung>,
+    #[serde(default)]
+    pub children: Vec<VorlagenKnoten>,
+}
+
+impl VorlagenKnoten {
+    /// Anzahl der Kanaele im Teilbaum (inkl. diesem Knoten)
+    pub fn anzahl_kanaele(&self) -> usize {
+        1 + self
+            .children
+            .iter()
+            .map(Self::anzahl_kanaele)
+            .sum::<usize>()
+    }
+
+    /// Tiefe des Teilbaums (ein Blatt hat Tiefe 1)
+    pub fn tiefe(&self) -> u32 {
+        1 + self.children.iter().map(Self::tiefe).max().unwrap_or(0)
+    }
+
+    /// Prueft die Definition rekursiv (Namen, Codec-Profile, Limits)
+    pub fn pruefen(&self) -> Result<(), String> {
+        let name = self.name.trim();
+        if name.is_empty() {
+            return Err("Kanalname darf nicht leer sein".into());
+        }
+        if name.chars().count() > MAX_VORLAGEN_KANALNAME {
+            return Err(format!(
+                "Kanalname '{name}' ist laenger als {MAX_VORLAGEN_KANALNAME} Zeichen"
+            ));
+        }
+        if self.max_clients < 0 {
+            return Err(format!(
+                "Kanal '{name}': max_clients darf nicht negativ sein"
+            ));
+        }
+        if let Some(profil) = &self.codec_profile {
+            if !CODEC_PROFILE.contains(&profil.as_str()) {
+                return Err(format!(
+                    "Kanal '{name}': unbekanntes Codec-Profil '{profil}'"
+                ));
+            }
+        }
+        for b in &self.permissions {
+            if b.permission_key.trim().is_empty() {
+                return Err(format!("Kanal '{name}': leerer Berechtigungsschluessel"));
+            }
+            if let VorlagenZiel::ChannelGroup(g) | VorlagenZiel::ServerGroup(g) = &b.target {
+                if g.trim().is_empty() {
+                    return Err(format!("Kanal '{name}': leerer Gruppenname"));
+                }
+            }
+        }
+        self.children.iter().try_for_each(Self::pruefen)
+    }
+}
+
+/// Berechtigung in einer Vorlage (wird beim Instanziieren auf den Kanal gesetzt)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VorlagenBerechtigung {
+    pub target: VorlagenZiel,
+    pub permission_key: String,
+    pub wert: BerechtigungsWert,
+}
+
+/// Ziel einer Vorlagen-Berechtigung; Gruppen werden per Name referenziert
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", content = "name", rename_all = "snake_case")]
+pub enum VorlagenZiel {
+    /// Standard fuer alle Benutzer im Kanal
+    ChannelDefault,
+    /// Kanal-Gruppe (Name muss beim Instanziieren existieren)
+    ChannelGroup(String),
+    /// Server-Gruppe (Name muss beim Instanziieren existieren)
+    ServerGroup(String),
+}
+
+/// Kanal-Vorlagen-Datensatz
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KanalVorlageRecord {
+    pub id: Uuid,
+    pub name: String,
+    pub description: Option<String>,
+    pub root: VorlagenKnoten,
+    /// Mitgelieferte Vorlage (nicht loeschbar)
+    pub builtin: bool,
+    pub created_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Daten zum Erstellen einer neuen Kanal-Vorlage
+#[derive(Debug, Clone)]
+pub struct NeueKanalVorlage<'a> {
+    pub name: &'a str,
+    pub description: Option<&'a str>,
+    pub root: VorlagenKnoten,
+    pub created_by: Option<Uuid>,
+}
+
+/// Grenzen fuer das Instanziieren eines Kanalbaums (0 = unbegrenzt)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct KanalbaumGrenzen {
+    /// Maximale Gesamtanzahl an Kanaelen auf dem Server
+    pub max_kanaele: u32,
+    /// Maximale Verschachtelungstiefe (Kanal auf Root-Ebene = 1)
+    pub max_tiefe: u32,
+}
+
+// ---------------------------------------------------------------------------
+// Sammel-Import (z.B. Migration von anderen Servern)
+// ---------------------------------------------------------------------------
+
+/// Server-Gruppe samt Berechtigungen fuer einen Sammel-Import
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportServerGruppe {
+    pub name: String,
+    pub priority: i64,
+    /// Server-weite Regeln der Gruppe (Key, Wert)
+    pub berechtigungen: Vec<(String, BerechtigungsWert)>,
+}
+
+/// Benutzer samt Server-Gruppen fuer einen Sammel-Import
+///
+/// Importierte Benutzer muessen ihr Passwort beim ersten Login aendern
+/// (`password_changed = false`).
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportBenutzer {
+    pub username: String,
+    pub password_hash: String,
+    pub gruppen: Vec<Uuid>,
+}
+
+// ---------------------------------------------------------------------------
+// Server-Einstellungen
+// ---------------------------------------------------------------------------
+
+/// Eintrag der Server-Einstellungen (Schluessel/Wert)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EinstellungRecord {
+    pub key: String,
+    pub value: String,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Eintrag im Neustart-Protokoll (ein Eintrag pro Serverstart)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ServerStartRecord {
+    pub id: Uuid,
+    pub started_at: DateTime<Utc>,
+    /// Server-Version dieses Starts
+    pub version: String,
+    /// Ob der vorherige Lauf sauber heruntergefahren wurde
+    pub clean_shutdown: bool,
+    /// Pruefsumme der geladenen Konfiguration
+    pub config_hash: String,
+}
+
+/// Daten fuer einen neuen Eintrag im Neustart-Protokoll
+#[derive(Debug, Clone)]
+pub struct NeuerServerStart<'a> {
+    pub started_at: DateTime<Utc>,
+    pub version: &'a str,
+    pub clean_shutdown: bool,
+    pub config_hash: &'a str,
+}
+
+// ---------------------------------------------------------------------------
+// Geplante Aktionen
+// ---------------------------------------------------------------------------
+
+/// Aktion, die der Zeitplaner ausfuehrt (wird als JSON gespeichert)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum GeplanteAktion {
+    /// Kanalbaum aus einer Vorlage anlegen
+    KanalAusVorlage {
+        vorlage_id: Uuid,
+        #[serde(default)]
+        parent_id: Option<Uuid>,
+        #[serde(default)]
+        name_prefix: Option<String>,
+        /// Angelegte Kanaele nach so vielen Minuten wieder loeschen (zeitlich begrenzt)
+        #[serde(default)]
+        loeschen_nach_min: Option<u32>,
+    },
+    /// Kanaele loeschen, in der angegebenen Reihenfolge
+    KanaeleLoeschen { kanal_ids: Vec<Uuid> },
+}
+
+impl GeplanteAktion {
+    /// Kurzname fuer Listen und Audit-Log
+    pub fn typ(&self) -> &'static str {
+        match self {
+            Self::KanalAusVorlage { .. } => "kanal_aus_vorlage",
+            Self::KanaeleLoeschen { .. } => "kanaele_loeschen",
+        }
+    }
+}
+
+/// Datensatz einer geplanten Aktion
+#[derive(Debug, Clone)]
+pub struct GeplanteAktionRecord {
+    pub id: Uuid,
+    pub name: String,
+    pub aktion: GeplanteAktion,
+    pub zeitplan: Zeitplan,
+    /// Naechste faellige Ausfuehrung; `None` = erledigt oder abgebrochen
+    pub next_run_at: Option<DateTime<Utc>>,
+    pub last_run_at: Option<DateTime<Utc>>,
+    /// Ausgefuehrt wird im Namen dieses Benutzers
+    pub created_by: Uuid,
+    /// Scopes des anlegenden API-Tokens; `None` = per Session angelegt
+    pub scopes: Option<Vec<String>>,
+    pub cancelled: bool,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Daten zum Anlegen einer geplanten Aktion
+#[derive(Debug, Clone)]
+pub struct NeueGeplanteAktion<'a> {
+    pub name: &'a str,
+    pub aktion: GeplanteAktion,
+    pub zeitplan: Zeitplan,
+    /// Erste Ausfuehrung (siehe [`Zeitplan::erste_ausfuehrung`])
+    pub next_run_at: DateTime<Utc>,
+    pub created_by: Uuid,
+    pub scopes: Option<&'a [String]>,
+}
+
+// ---------------------------------------------------------------------------
+// Konten (Datenexport und Loeschung)
+// ---------------------------------------------------------------------------
+
+/// Platzhalter-Absender fuer Nachrichten geloeschter Konten
+///
+/// Legt Migration 9 an; der Benutzer ist inaktiv, kann sich nicht anmelden
+/// und erscheint nicht in Benutzerlisten.
+pub const GELOESCHTER_BENUTZER: Uuid = Uuid::nil();
+
+/// Umgang mit den Nachrichten eines geloeschten Kontos
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NachrichtenRichtlinie {
+    /// Inhalt bleibt erhalten, nur der Absender wird ersetzt
+    #[default]
+    Behalten,
+    /// Inhalt wird geleert und die Nachricht als geloescht markiert
+    Leeren,
+}
+
+impl NachrichtenRichtlinie {
+    pub fn als_str(&self) -> &'static str {
+        match self {
+            Self::Behalten => "behalten",
+            Self::Leeren => "leeren",
+        }
+    }
+}
+
+/// Alle gespeicherten Daten eines Benutzers (Inhalt des Export-Archivs)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KontoDaten {
+    pub profil: KontoProfil,
+    /// Verfasste Nachrichten, aelteste zuerst (inkl. geloeschter)
+    pub nachrichten: Vec<KontoNachricht>,
+    /// Metadaten hochgeladener Dateien, aelteste zuerst
+    pub dateien: Vec<KontoDatei>,
+    /// Audit-Eintraege mit dem Benutzer als Handelndem, aelteste zuerst
+    pub audit: Vec<AuditLogRecord>,
+}
+
+/// Profil eines Benutzers im Export (ohne Passwort-Hash)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KontoProfil {
+    pub id: Uuid,
+    pub username: String,
+    pub created_at: DateTime<Utc>,
+    pub last_login: Option<DateTime<Utc>>,
+    pub is_active: bool,
+    /// Namen der Server-Gruppen
+    pub server_gruppen: Vec<String>,
+}
+
+/// Verfasste Nachricht im Export
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KontoNachricht {
+    pub id: Uuid,
+    pub channel_id: Uuid,
+    pub kanal_name: String,
+    pub content: String,
+    pub message_type: NachrichtenTyp,
+    pub reply_to: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub edited_at: Option<DateTime<Utc>>,
+    pub deleted_at: Option<DateTime<Utc>>,
+}
+
+/// Metadaten einer hochgeladenen Datei im Export
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KontoDatei {
+    pub id: Uuid,
+    pub channel_id: Uuid,
+    pub kanal_name: String,
+    pub filename: String,
+    pub mime_type: String,
+    pub size_bytes: i64,
+    pub checksum: String,
+    pub created_at: DateTime<Utc>,
+    pub deleted_at: Option<DateTime<Utc>>,
+}
+
+/// Datensatz eines Konto-Exports
+#[derive(Debug, Clone)]
+pub struct KontoExportRecord {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub storage_path: String,
+    /// `None` solange das Archiv erstellt wird
+    pub size_bytes: Option<i64>,
+    pub created_at: DateTime<Utc>,
+    pub completed_at: Option<DateTime<Utc>>,
+    pub expires_at: DateTime<Utc>,
+    pub downloaded_at: Option<DateTime<Utc>>,
+}
+
+/// Daten zum Anlegen eines Konto-Exports
+#[derive(Debug, Clone)]
+pub struct NeuerKontoExport<'a> {
+    pub user_id: Uuid,
+    /// SHA-256 (hex) des Download-Tokens
+    pub token_hash: &'a str,
+    pub storage_path: &'a str,
+    pub expires_at: DateTime<Utc>,
+    /// Kein neuer Export, wenn seit diesem Zeitpunkt schon einer angelegt
+    /// wurde (`None` = ohne Begrenzung)
+    pub gesperrt_seit: Option<DateTime<Utc>>,
+}
+
+/// Auftrag zum Loeschen eines Kontos
+#[derive(Debug, Clone)]
+pub struct KontoLoeschAuftrag<'a> {
+    pub user_id: Uuid,
+    pub richtlinie: NachrichtenRichtlinie,
+    /// Ausloesender Administrator (`None` = der Benutzer selbst)
+    pub admin_id: Option<Uuid>,
+    /// Kontingent-Gruppe, der die geloeschten Dateien angerechnet wurden
+    pub kontingent_gruppe: &'a str,
+}
+
+/// Ergebnis einer Kontoloeschung
+///
+/// Die Speicherdateien von `dateien` und `exporte` muss der Aufrufer
+/// entfernen; die Datenbank kennt nur ihre Pfade.
+#[derive(Debug, Clone, Default)]
+pub struct KontoLoeschung {
+    /// Anonymisierte Nachrichten
+    pub nachrichten: u64,
+    /// Geloeschte Datei-Datensaetze
+    pub dateien: Vec<DateiRecord>,
+    /// Entfernte Berechtigungen und Gruppenmitgliedschaften
+    pub berechtigungen: u64,
+    /// Speicherpfade entfernter Export-Archive
+    pub exporte: Vec<String>,
+}
+
+/// Auftrag zum Bereinigen der Inhalte eines Benutzers
+#[derive(Debug, Clone)]
+pub struct NeueInhaltsBereinigung {
+    pub user_id: Uuid,
+    /// Nur Inhalte mit `created_at >= seit`
+    pub seit: DateTime<Utc>,
+    /// Nur in diesem Kanal (`None` = alle Kanaele)
+    pub channel_id: Option<Uuid>,
+    /// Ausloesender Moderator
+    pub angefordert_von: Option<Uuid>,
+}
+
+/// Bereinigungsauftrag mit bisherigem Fortschritt
+#[derive(Debug, Clone, PartialEq)]
+pub struct InhaltsBereinigungRecord {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub seit: DateTime<Utc>,
+    pub channel_id: Option<Uuid>,
+    pub angefordert_von: Option<Uuid>,
+    /// Bisher geloeschte Nachrichten
+    pub nachrichten: i64,
+    /// Bisher geloeschte Dateien
+    pub dateien: i64,
+    pub created_at: DateTime<Utc>,
+    pub completed_at: Option<DateTime<Utc>>,
+}
+
+/// Von einem Bereinigungs-Stapel weich geloeschte Inhalte
+///
+/// Die Speicherdateien von `dateien` muss der Aufrufer entfernen.
+#[derive(Debug, Clone, Default)]
+pub struct InhaltsStapel {
+    pub nachrichten: Vec<ChatNachrichtRecord>,
+    pub dateien: Vec<DateiRecord>,
+}
+
+impl InhaltsStapel {
+    /// `true` wenn nichts mehr zu loeschen war
+    pub fn ist_leer(&self) -> bool {
+        self.nachrichten.is_empty() && self.dateien.is_empty()
+    }
+}
@@ -0,0 +1,267 @@
+//! Nachricht des Tages (MOTD)
+//!
+//! Die MOTD ist ein Markdown-Text mit Versionsnummer und liegt wie die
+//! uebrigen Laufzeit-Einstellungen in `server_settings`. Die Version steigt
+//! nur, wenn sich der (bereinigte) Text tatsaechlich aendert – Clients zeigen
+//! eine bereits bestaetigte Version nicht erneut an.
+//!
+//! Gespeichert wird nur eine Markdown-Teilmenge ([`bereinigen`]): Hervorhebungen,
+//! Ueberschriften, Listen, Zitate, Code und Links auf `http`, `https` und
+//! `mailto`. Rohes HTML wird maskiert, Bilder werden zu Links.
+
+use serde::{Deserialize, Serialize};
+
+use crate::einstellungen::schluessel;
+use crate::error::DbError;
+use crate::repository::{DbResult, SettingsRepository};
+
+/// Groesste MOTD in Bytes (vor und nach der Bereinigung)
+pub const MAX_MOTD_BYTES: usize = 64 * 1024;
+
+/// Link-Schemata, die in der MOTD erhalten bleiben
+const ERLAUBTE_SCHEMATA: &[&str] = &["http", "https", "mailto"];
+
+/// Gespeicherte Nachricht des Tages
+///
+/// Version 0 mit leerem Text bedeutet: noch nie gesetzt.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Motd {
+    pub markdown: String,
+    pub version: u64,
+}
+
+impl Motd {
+    /// Gibt `true` zurueck wenn keine Nachricht angezeigt werden soll
+    pub fn ist_leer(&self) -> bool {
+        self.markdown.is_empty()
+    }
+
+    /// Laedt die MOTD (fehlende Schluessel ergeben eine leere Nachricht)
+    pub async fn laden<S: SettingsRepository + ?Sized>(repo: &S) -> DbResult<Self> {
+        let markdown = repo.get_string(schluessel::MOTD, "").await?;
+        let version = match repo.get(schluessel::MOTD_VERSION).await? {
+            None => 0,
+            Some(wert) => wert.trim().parse().map_err(|_| {
+                DbError::UngueltigeDaten(format!("MOTD-Version ist keine Zahl: '{wert}'"))
+            })?,
+        };
+        Ok(Self { markdown, version })
+    }
+
+    /// Bereinigt und speichert einen neuen Text
+    ///
+    /// Gibt die gespeicherte MOTD zurueck und ob sich der Text geaendert hat.
+    /// Ein unveraenderter Text behaelt seine Version. Aufrufer muessen
+    /// gleichzeitige Aufrufe serialisieren (siehe
+    /// [`EinstellungsCache::motd_setzen`](crate::einstellungen::EinstellungsCache::motd_setzen)).
+    pub async fn setzen<S: SettingsRepository + ?Sized>(
+        repo: &S,
+        markdown: &str,
+    ) -> DbResult<(Self, bool)> {
+        groesse_pruefen(markdown)?;
+        let markdown = bereinigen(markdown);
+        groesse_pruefen(&markdown)?;
+
+        let alt = Self::laden(repo).await?;
+        if alt.markdown == markdown {
+            return Ok((alt, false));
+        }
+        let neu = Self {
+            markdown,
+            version: alt.version + 1,
+        };
+        // Version zuerst: scheitert danach der Text, wird hoechstens der alte
+        // Text erneut angezeigt statt ein neuer verschluckt
+        repo.set(schluessel::MOTD_VERSION, &neu.version.to_string())
+            .await?;
+        repo.set(schluessel::MOTD, &neu.markdown).await?;
+        Ok((neu, true))
+    }
+}
+
+fn groesse_pruefen(markdown: &str) -> DbResult<()> {
+    if markdown.len() > MAX_MOTD_BYTES {
+        return Err(DbError::UngueltigeDaten(format!(
+            "MOTD ist groesser als {MAX_MOTD_BYTES} Bytes ({} Bytes)",
+            markdown.len()
+        )));
+    }
+    Ok(())
+}
+
+/// Reduziert Markdown auf die fuer die MOTD erlaubte Teilmenge
+///
+/// - Steuerzeichen (ausser Tab) entfallen, Zeilenenden werden zu `\n`
+/// - `<` ausserhalb von Code wird zu `&lt;` (kein rohes HTML, keine Autolinks)
+/// - Links mit anderem Ziel als `http`, `https` oder `mailto` verlieren das
+///   Ziel und bleiben als Text stehen, ebenso Referenz-Definitionen
+/// - Bilder werden zu Links (keine externen Ressourcen beim Anzeigen)
+///
+/// Code-Bloecke muessen mit Zaeunen (```` ``` ```` oder `~~~`) markiert sein;
+/// eingerueckter Code wird wie Fliesstext behandelt.
+pub fn bereinigen(markdown: &str) -> String {
+    let mut zeilen = Vec::new();
+    let mut zaun: Option<&str> = None;
+    for zeile in markdown.lines() {
+        let zeile: String = zeile
+            .chars()
+            .filter(|c| *c == '\t' || !c.is_control())
+            .collect();
+        let eingerueckt = zeile.trim_start();
+        let zaun_zeile = ["```", "~~~"]
+            .into_iter()
+            .find(|z| eingerueckt.starts_with(z));
+
+        match (zaun, zaun_zeile) {
+            (None, Some(z)) => {
+                zaun = Some(z);
+                zeilen.push(zeile);
+            }
+            (Some(offen), Some(z)) if offen == z => {
+                zaun = None;
+                zeilen.push(zeile);
+            }
+            (Some(_), _) => zeilen.push(zeile),
+            (None, None) => {
+                if referenz_ziel(&zeile).is_some_and(|ziel| !ziel_erlaubt(ziel)) {
+                    continue;
+                }
+                zeilen.push(inline_bereinigen(&zeile));
+            }
+        }
+    }
+    let mut text = zeilen.join("\n");
+    text.truncate(text.trim_end().len());
+    text
+}
+
+/// Ziel einer Referenz-Definition (`[name]: ziel`)
+fn referenz_ziel(zeile: &str) -> Option<&str> {
+    let rest = zeile.trim_start().strip_prefix('[')?;
+    let (_, ziel) = rest.split_once("]:")?;
+    Some(ziel)
+}
+
+/// Prueft das Schema eines Link-Ziels (relative Ziele sind im Client sinnlos)
+fn ziel_erlaubt(ziel: &str) -> bool {
+    let ziel = ziel.split_whitespace().next().unwrap_or_default();
+    let ziel = ziel.trim_start_matches('<');
+    match ziel.split_once(':') {
+        Some((schema, _)) if !schema.contains(['/', '?', '#']) => ERLAUBTE_SCHEMATA
+            .iter()
+            .any(|erlaubt| schema.eq_ignore_ascii_case(erlaubt)),
+        _ => false,
+    }
+}
+
+/// Bereinigt eine Zeile ausserhalb eines Code-Blocks
+fn inline_bereinigen(zeile: &str) -> String {
+    let zeichen: Vec<char> = zeile.chars().collect();
+    let mut aus = String::with_capacity(zeile.len());
+    let mut i = 0;
+    while i < zeichen.len() {
+        match zeichen[i] {
+            '`' => {
+                let lauf = zeichen[i..].iter().take_while(|c| **c == '`').count();
+                let ende = code_ende(&zeichen, i + lauf, lauf).unwrap_or(i + lauf);
+                aus.extend(&zeichen[i..ende]);
+                i = ende;
+            }
+            '\\' if i + 1 < zeichen.len() => {
+                aus.extend(&zeichen[i..i + 2]);
+                i += 2;
+            }
+            '<' => {
+                aus.push_str("&lt;");
+                i += 1;
+            }
+            '!' if zeichen.get(i + 1) == Some(&'[') => i += 1,
+            ']' if zeichen.get(i + 1) == Some(&'(') => match klammer_ende(&zeichen, i + 2) {
+                Some(ende) => {
+                    let ziel: String = zeichen[i + 2..ende].iter().collect();
+                    if ziel_erlaubt(&ziel) {
+                        aus.push_str("](");
+                        aus.push_str(&ziel);
+                        aus.push(')');
+                    } else {
+                        aus.push(']');
+                    }
+                    i = ende + 1;
+                }
+                None => {
+                    aus.push(']');
+                    i += 1;
+                }
+            },
+            c => {
+                aus.push(c);
+                i += 1;
+            }
+        }
+    }
+    aus
+}
+
+/// Ende (exklusiv) eines Code-Spans, der mit `lauf` Backticks beginnt
+fn code_ende(zeichen: &[char], ab: usize, lauf: usize) -> Option<usize> {
+    let mut i = ab;
+    while i < zeichen.len() {
+        if zeichen[i] == '`' {
+            let laenge = zeichen[i..].iter().take_while(|c| **c == '`').count();
+            if laenge == lauf {
+                return Some(i + laenge);
+            }
+            i += laenge;
+        } else {
+            i += 1;
+        }
+    }
+    None
+}
+
+/// Position der schliessenden Klammer eines Link-Ziels (verschachtelt erlaubt)
+fn klammer_ende(zeichen: &[char], ab: usize) -> Option<usize> {
+    let mut tiefe = 0usize;
+    for (i, c) in zeichen.iter().enumerate().skip(ab) {
+        match c {
+            '(' => tiefe += 1,
+            ')' if tiefe == 0 => return Some(i),
+            ')' => tiefe -= 1,
+            _ => {}
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn erlaubte_teilmenge_bleibt_erhalten() {
+        let text = "# Willkommen\n\n**Wichtig:** `a < b` und [Regeln](https://example.org/r(1))\n- Punkt\n> Zitat";
+        assert_eq!(bereinigen(text), text);
+    }
+
+    #[test]
+    fn html_und_gefaehrliche_links_werden_entschaerft() {
+        assert_eq!(
+            bereinigen("<script>alert(1)</script> [klick](javascript:alert(1))"),
+            "&lt;script>alert(1)&lt;/script> [klick]"
+        );
+        assert_eq!(
+            bereinigen("![bild](https://example.org/x.png) [m](MAILTO:a@b.de)"),
+            "[bild](https://example.org/x.png) [m](MAILTO:a@b.de)"
+        );
+        assert_eq!(bereinigen("[x]: data:text/html,hallo\ntext"), "text");
+        assert_eq!(bereinigen("[rel](/pfad) \\<b>"), "[rel] \\<b>");
+    }
+
+    #[test]
+    fn code_bloecke_und_steuerzeichen() {
+        assert_eq!(
+            bereinigen("```\n<b>roh</b>\n```\n<i>\u{7}\r\n\n\n"),
+            "```\n<b>roh</b>\n```\n&lt;i>"
+        );
+    }
+}
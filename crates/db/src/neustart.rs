@@ -0,0 +1,158 @@
+//! Neustart-Protokoll mit Shutdown-Marker
+//!
+//! Jeder Serverstart landet als Eintrag in `server_starts`. Ob der vorherige
+//! Lauf sauber endete, verraet eine Marker-Datei: Beim Start steht darin
+//! `laufend`, erst das geordnete Herunterfahren ersetzt das durch `sauber`.
+//! Findet ein Start etwas anderes vor – `laufend`, eine unlesbare oder
+//! beschaedigte Datei –, gilt der vorherige Lauf als abgestuerzt. Eine
+//! fehlende Datei ist nur beim allerersten Start unverdaechtig.
+//!
+//! Der Marker wird ueber eine temporaere Datei und `rename` ersetzt, damit
+//! ein Absturz mitten im Schreiben keinen halben `sauber`-Marker hinterlaesst.
+
+use std::io;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+
+use crate::models::{NeuerServerStart, ServerStartRecord};
+use crate::repository::{DbResult, ServerStartRepository};
+
+const MARKER_SAUBER: &str = "sauber";
+const MARKER_LAUFEND: &str = "laufend";
+
+/// Inhalt der Marker-Datei beim Start
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MarkerZustand {
+    /// Keine Marker-Datei vorhanden
+    Fehlt,
+    /// Vorheriger Lauf wurde geordnet beendet
+    Sauber,
+    /// Server lief noch, oder der Marker ist nicht lesbar
+    Unsauber,
+}
+
+/// Liest den Marker; alles ausser einem gueltigen `sauber`-Eintrag ist unsauber
+pub fn marker_lesen(pfad: &Path) -> MarkerZustand {
+    match std::fs::read(pfad) {
+        Ok(inhalt) => marker_auswerten(&inhalt),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => MarkerZustand::Fehlt,
+        Err(e) => {
+            tracing::warn!(pfad = %pfad.display(), fehler = %e, "Shutdown-Marker nicht lesbar");
+            MarkerZustand::Unsauber
+        }
+    }
+}
+
+/// Erwartet genau eine Zeile `<zustand> <RFC-3339-Zeitpunkt>`
+fn marker_auswerten(inhalt: &[u8]) -> MarkerZustand {
+    let Ok(text) = std::str::from_utf8(inhalt) else {
+        return MarkerZustand::Unsauber;
+    };
+    let mut teile = text.trim_end_matches('\n').split(' ');
+    let (Some(zustand), Some(zeitpunkt), None) = (teile.next(), teile.next(), teile.next()) else {
+        return MarkerZustand::Unsauber;
+    };
+    if zustand == MARKER_SAUBER && DateTime::parse_from_rfc3339(zeitpunkt).is_ok() {
+        MarkerZustand::Sauber
+    } else {
+        MarkerZustand::Unsauber
+    }
+}
+
+/// Ersetzt den Marker atomar (legt fehlende Verzeichnisse an)
+fn marker_schreiben(pfad: &Path, zustand: &str) -> io::Result<()> {
+    if let Some(verzeichnis) = pfad.parent().filter(|v| !v.as_os_str().is_empty()) {
+        std::fs::create_dir_all(verzeichnis)?;
+    }
+    let mut temp = PathBuf::from(pfad);
+    temp.as_mut_os_string().push(".tmp");
+    std::fs::write(&temp, format!("{zustand} {}\n", Utc::now().to_rfc3339()))?;
+    std::fs::rename(&temp, pfad)
+}
+
+/// Markiert den laufenden Server als geordnet beendet
+///
+/// Als letzter Schritt des Herunterfahrens aufrufen.
+pub fn sauber_beendet(pfad: &Path) -> io::Result<()> {
+    marker_schreiben(pfad, MARKER_SAUBER)
+}
+
+/// Haelt einen Serverstart fest und setzt den Marker auf `laufend`
+///
+/// Gibt den neuen Eintrag zurueck; `clean_shutdown` ist `false`, wenn der
+/// vorherige Lauf nicht geordnet endete. Laesst sich der Marker nicht
+/// schreiben, wird nur gewarnt – der naechste Absturz bliebe dann unerkannt.
+pub async fn start_erfassen<R: ServerStartRepository + ?Sized>(
+    repo: &R,
+    marker: &Path,
+    version: &str,
+    config_hash: &str,
+) -> DbResult<ServerStartRecord> {
+    let clean_shutdown = match marker_lesen(marker) {
+        MarkerZustand::Sauber => true,
+        MarkerZustand::Unsauber => false,
+        // Ohne Marker ist nur der allererste Start unverdaechtig
+        MarkerZustand::Fehlt => repo.list_recent(1).await?.is_empty(),
+    };
+
+    let eintrag = repo
+        .create(NeuerServerStart {
+            started_at: Utc::now(),
+            version,
+            clean_shutdown,
+            config_hash,
+        })
+        .await?;
+
+    if let Err(e) = marker_schreiben(marker, MARKER_LAUFEND) {
+        tracing::warn!(
+            pfad = %marker.display(),
+            fehler = %e,
+            "Shutdown-Marker konnte nicht geschrieben werden, Abstuerze werden nicht erkannt"
+        );
+    }
+    Ok(eintrag)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nur_gueltiger_sauber_marker_ist_sauber() {
+        assert_eq!(
+            marker_auswerten(b"sauber 2024-05-01T12:00:00+00:00\n"),
+            MarkerZustand::Sauber
+        );
+        assert_eq!(
+            marker_auswerten(b"laufend 2024-05-01T12:00:00+00:00\n"),
+            MarkerZustand::Unsauber
+        );
+        for kaputt in [
+            &b""[..],
+            b"sauber",
+            b"sauber gestern",
+            b"sauber 2024-05-01T12:00:00+00:00 extra",
+            b"sau\xffber 2024-05-01T12:00:00+00:00",
+            b"\0\0\0\0",
+        ] {
+            assert_eq!(marker_auswerten(kaputt), MarkerZustand::Unsauber);
+        }
+    }
+
+    #[test]
+    fn sauber_beendet_ueberschreibt_marker() {
+        let verzeichnis =
+            std::env::temp_dir().join(format!("speakeasy-marker-{}", uuid::Uuid::new_v4()));
+        let pfad = verzeichnis.join("unter").join("shutdown.marker");
+        assert_eq!(marker_lesen(&pfad), MarkerZustand::Fehlt);
+
+        marker_schreiben(&pfad, MARKER_LAUFEND).unwrap();
+        assert_eq!(marker_lesen(&pfad), MarkerZustand::Unsauber);
+        sauber_beendet(&pfad).unwrap();
+        assert_eq!(marker_lesen(&pfad), MarkerZustand::Sauber);
+
+        let _ = std::fs::remove_dir_all(verzeichnis);
+    }
+}
@@ -10,8 +10,12 @@
 //!   5. Server-Default (globale Standardberechtigungen)
 //!
 //! Merge-Regel bei Konflikten auf gleicher Ebene: Deny > Grant > Skip
+//!
+//! [`berechtigungen_nachverfolgen`] fuehrt dieselbe Aufloesung mit Herkunft
+//! aller Regeln durch (Diagnose "effektive Berechtigungen"). Der normale
+//! Pruefpfad nutzt weiterhin [`berechtigungen_aufloesen`].
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
 use crate::models::{BerechtigungsWert, TriState};
 
@@ -24,6 +28,43 @@ pub struct AufgeloesteBerechtigung {
     pub stufe: BerechtigungsStufe,
 }
 
+/// Alle Berechtigungs-Keys, die der Server prueft
+///
+/// Die Diagnose der effektiven Berechtigungen listet jeden dieser Keys, auch
+/// wenn auf keiner Ebene eine Regel existiert.
+pub const BERECHTIGUNGS_KATALOG: &[&str] = &[
+    "b_afk_exempt",
+    "b_channel_create",
+    "b_channel_delete",
+    "b_channel_emergency_mute",
+    "b_channel_emergency_mute_bypass",
+    "b_channel_invite",
+    "b_channel_join",
+    "b_channel_join_approve",
+    "b_channel_join_ignore_maxclients",
+    "b_channel_join_ignore_password",
+    "b_channel_modify",
+    "b_chat_moderate",
+    "b_chat_slowmode_bypass",
+    "b_client_ban_server",
+    "b_client_kick_channel",
+    "b_client_kick_server",
+    "b_client_move",
+    "b_client_poke",
+    "b_permission_modify",
+    "b_permission_read",
+    "b_permission_view",
+    "b_server_alerts_receive",
+    "b_server_modify",
+    "b_server_stop",
+    "b_soundboard_play",
+    "b_voice_transmit",
+    "i_channel_max_clients",
+    "i_client_move_power",
+    "i_client_needed_move_power",
+    "i_upload_limit",
+];
+
 /// Hierarchie-Stufe einer aufgeloesten Berechtigung
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum BerechtigungsStufe {
@@ -34,6 +75,16 @@ pub enum BerechtigungsStufe {
     ServerDefault,
 }
 
+impl BerechtigungsStufe {
+    /// Geltungsbereich der Regel: `"kanal"` oder `"server"`
+    pub fn geltungsbereich(&self) -> &'static str {
+        match self {
+            Self::Individual | Self::KanalGruppe | Self::KanalDefault => "kanal",
+            Self::ServerGruppe { .. } | Self::ServerDefault => "server",
+        }
+    }
+}
+
 impl std::fmt::Display for BerechtigungsStufe {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -114,6 +165,69 @@ pub fn berechtigungen_aufloesen(
     ergebnis
 }
 
+/// Eine Regel, die bei der Aufloesung eines Keys betrachtet wurde
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpurEintrag {
+    pub stufe: BerechtigungsStufe,
+    pub wert: BerechtigungsWert,
+}
+
+/// Nachvollziehbare Aufloesung eines Berechtigungs-Keys
+#[derive(Debug, Clone, PartialEq)]
+pub struct BerechtigungsSpur {
+    pub permission_key: String,
+    /// Regel, die entschieden hat (`None` = nirgends gesetzt, Standard gilt)
+    pub entscheidend: Option<SpurEintrag>,
+    /// Alle Regeln fuer den Key in Pruefreihenfolge, inklusive Skip und
+    /// ueberstimmter Regeln
+    pub regeln: Vec<SpurEintrag>,
+}
+
+/// Fuehrt die Aufloesung mit Herkunft fuer alle Keys durch
+///
+/// Enthaelt jeden Key aus [`BERECHTIGUNGS_KATALOG`] sowie alle Keys, fuer die
+/// eine Regel existiert, sortiert nach Key. Das Ergebnis stimmt mit
+/// [`berechtigungen_aufloesen`] ueberein.
+pub fn berechtigungen_nachverfolgen(eingabe: &BerechtigungsEingabe) -> Vec<BerechtigungsSpur> {
+    let mut stufen: Vec<(BerechtigungsStufe, &[(String, BerechtigungsWert)])> = vec![
+        (BerechtigungsStufe::Individual, &eingabe.individual),
+        (
+            BerechtigungsStufe::KanalGruppe,
+            eingabe.kanal_gruppe.as_deref().unwrap_or_default(),
+        ),
+        (BerechtigungsStufe::KanalDefault, &eingabe.kanal_default),
+    ];
+    for (name, perms) in &eingabe.server_gruppen {
+        stufen.push((
+            BerechtigungsStufe::ServerGruppe { name: name.clone() },
+            perms,
+        ));
+    }
+    stufen.push((BerechtigungsStufe::ServerDefault, &eingabe.server_default));
+
+    let mut regeln: BTreeMap<&str, Vec<SpurEintrag>> = BERECHTIGUNGS_KATALOG
+        .iter()
+        .map(|key| (*key, Vec::new()))
+        .collect();
+    for (stufe, perms) in &stufen {
+        for (key, wert) in perms.iter() {
+            regeln.entry(key).or_default().push(SpurEintrag {
+                stufe: stufe.clone(),
+                wert: wert.clone(),
+            });
+        }
+    }
+
+    regeln
+        .into_iter()
+        .map(|(key, regeln)| BerechtigungsSpur {
+            permission_key: key.to_string(),
+            entscheidend: regeln.iter().find(|r| ist_aktiv(&r.wert)).cloned(),
+            regeln,
+        })
+        .collect()
+}
+
 /// Prueft ob ein Berechtigungswert "aktiv" ist (nicht Skip)
 fn ist_aktiv(wert: &BerechtigungsWert) -> bool {
     match wert {
@@ -304,4 +418,59 @@ mod tests {
         let perm = ergebnis.get("can_ban").unwrap();
         assert_eq!(perm.wert, grant());
     }
+
+    #[test]
+    fn nachverfolgen_stimmt_mit_aufloesung_ueberein() {
+        let eingabe = BerechtigungsEingabe {
+            individual: vec![("b_client_poke".into(), skip())],
+            kanal_gruppe: Some(vec![(
+                "i_upload_limit".into(),
+                BerechtigungsWert::IntLimit(10),
+            )]),
+            kanal_default: vec![("i_upload_limit".into(), BerechtigungsWert::IntLimit(99))],
+            server_gruppen: vec![
+                ("Moderator".into(), vec![("b_client_poke".into(), deny())]),
+                ("Gast".into(), vec![("b_client_poke".into(), grant())]),
+            ],
+            server_default: vec![("eigener_key".into(), grant())],
+        };
+
+        let spuren = berechtigungen_nachverfolgen(&eingabe);
+        let aufgeloest = berechtigungen_aufloesen(&eingabe);
+
+        // Katalog plus nicht katalogisierte Keys, sortiert
+        assert!(spuren.len() > BERECHTIGUNGS_KATALOG.len());
+        assert!(spuren
+            .windows(2)
+            .all(|w| w[0].permission_key < w[1].permission_key));
+        for spur in &spuren {
+            let erwartet = aufgeloest
+                .get(&spur.permission_key)
+                .map(|a| (a.stufe.clone(), a.wert.clone()));
+            let ist = spur.entscheidend.clone().map(|e| (e.stufe, e.wert));
+            assert_eq!(ist, erwartet, "{}", spur.permission_key);
+        }
+
+        let poke = spuren
+            .iter()
+            .find(|s| s.permission_key == "b_client_poke")
+            .unwrap();
+        let entscheidend = poke.entscheidend.as_ref().unwrap();
+        assert_eq!(
+            entscheidend.stufe,
+            BerechtigungsStufe::ServerGruppe {
+                name: "Moderator".into()
+            }
+        );
+        assert_eq!(entscheidend.stufe.geltungsbereich(), "server");
+        // Skip und die ueberstimmte Gast-Regel bleiben sichtbar
+        assert_eq!(poke.regeln.len(), 3);
+
+        let join = spuren
+            .iter()
+            .find(|s| s.permission_key == "b_channel_join")
+            .unwrap();
+        assert!(join.entscheidend.is_none());
+        assert!(join.regeln.is_empty());
+    }
 }
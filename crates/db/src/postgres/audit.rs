@@ -0,0 +1,130 @@
+//! PostgreSQL-Implementierung des AuditLogRepository
+
+use sqlx::{Postgres, QueryBuilder, Row};
+use uuid::Uuid;
+
+use crate::models::{AuditLogFilter, AuditLogRecord, NeuerAuditEintrag};
+use crate::postgres::pool::{jetzt, PostgresDb};
+use crate::repository::{AuditLogRepository, DbResult};
+
+impl AuditLogRepository for PostgresDb {
+    async fn log_event(
+        &self,
+        actor_id: Option<Uuid>,
+        action: &str,
+        target_type: Option<&str>,
+        target_id: Option<&str>,
+        details: serde_json::Value,
+    ) -> DbResult<AuditLogRecord> {
+        let id = Uuid::new_v4();
+        let now = jetzt();
+
+        sqlx::query(
+            "INSERT INTO audit_log
+               (id, actor_id, action, target_type, target_id, details_json, timestamp)
+             VALUES ($1, $2, $3, $4, $5, $6, $7)",
+        )
+        .bind(id)
+        .bind(actor_id)
+        .bind(action)
+        .bind(target_type)
+        .bind(target_id)
+        .bind(&details)
+        .bind(now)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(AuditLogRecord {
+            id,
+            actor_id,
+            action: action.to_string(),
+            target_type: target_type.map(|s| s.to_string()),
+            target_id: target_id.map(|s| s.to_string()),
+            details,
+            timestamp: now,
+        })
+    }
+
+    async fn log_events(&self, eintraege: &[NeuerAuditEintrag]) -> DbResult<()> {
+        let mut tx = self.pool.begin().await?;
+        for eintrag in eintraege {
+            sqlx::query(
+                "INSERT INTO audit_log
+                   (id, actor_id, action, target_type, target_id, details_json, timestamp)
+                 VALUES ($1, $2, $3, $4, $5, $6, $7)",
+            )
+            .bind(Uuid::new_v4())
+            .bind(eintrag.actor_id)
+            .bind(&eintrag.action)
+            .bind(&eintrag.target_type)
+            .bind(&eintrag.target_id)
+            .bind(&eintrag.details)
+            .bind(eintrag.timestamp)
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn list_events(&self, filter: AuditLogFilter) -> DbResult<Vec<AuditLogRecord>> {
+        let mut q = QueryBuilder::<Postgres>::new(
+            "SELECT id, actor_id, action, target_type, target_id, details_json, timestamp
+             FROM audit_log",
+        );
+        filter_anhaengen(&mut q, &filter);
+        q.push(" ORDER BY timestamp DESC, seq DESC");
+        if let Some(limit) = filter.limit {
+            q.push(" LIMIT ").push_bind(limit);
+        }
+        if let Some(offset) = filter.offset {
+            q.push(" OFFSET ").push_bind(offset);
+        }
+
+        let rows = q.build().fetch_all(&self.pool).await?;
+        rows.iter().map(row_to_audit).collect()
+    }
+
+    async fn count_events(&self, filter: AuditLogFilter) -> DbResult<i64> {
+        let mut q = QueryBuilder::<Postgres>::new("SELECT COUNT(*) AS cnt FROM audit_log");
+        filter_anhaengen(&mut q, &filter);
+
+        let row = q.build().fetch_one(&self.pool).await?;
+        Ok(row.try_get("cnt")?)
+    }
+}
+
+/// Haengt die WHERE-Klausel fuer die gesetzten Filterfelder an
+fn filter_anhaengen(q: &mut QueryBuilder<'_, Postgres>, filter: &AuditLogFilter) {
+    q.push(" WHERE TRUE");
+    if let Some(v) = filter.actor_id {
+        q.push(" AND actor_id = ").push_bind(v);
+    }
+    if let Some(ref v) = filter.action {
+        q.push(" AND action = ").push_bind(v.clone());
+    }
+    if let Some(ref v) = filter.target_type {
+        q.push(" AND target_type = ").push_bind(v.clone());
+    }
+    if let Some(ref v) = filter.target_id {
+        q.push(" AND target_id = ").push_bind(v.clone());
+    }
+    if let Some(v) = filter.since {
+        q.push(" AND timestamp >= ").push_bind(v);
+    }
+    if let Some(v) = filter.until {
+        q.push(" AND timestamp <= ").push_bind(v);
+    }
+}
+
+pub(crate) fn row_to_audit(row: &sqlx::postgres::PgRow) -> DbResult<AuditLogRecord> {
+    Ok(AuditLogRecord {
+        id: row.try_get("id")?,
+        actor_id: row.try_get("actor_id")?,
+        action: row.try_get("action")?,
+        target_type: row.try_get("target_type")?,
+        target_id: row.try_get("target_id")?,
+        details: row.try_get("details_json")?,
+        timestamp: row.try_get("timestamp")?,
+    })
+}
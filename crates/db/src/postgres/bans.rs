@@ -0,0 +1,117 @@
+//! PostgreSQL-Implementierung des BanRepository
+
+use sqlx::Row;
+use uuid::Uuid;
+
+use crate::models::{BanRecord, NeuerBan};
+use crate::postgres::pool::{jetzt, PostgresDb};
+use crate::repository::{BanRepository, DbResult};
+
+impl BanRepository for PostgresDb {
+    async fn create(&self, data: NeuerBan<'_>) -> DbResult<BanRecord> {
+        let id = Uuid::new_v4();
+        let now = jetzt();
+
+        sqlx::query(
+            "INSERT INTO bans (id, user_id, ip, reason, banned_by, expires_at, created_at)
+             VALUES ($1, $2, $3, $4, $5, $6, $7)",
+        )
+        .bind(id)
+        .bind(data.user_id)
+        .bind(data.ip)
+        .bind(data.reason)
+        .bind(data.banned_by)
+        .bind(data.expires_at)
+        .bind(now)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(BanRecord {
+            id,
+            user_id: data.user_id,
+            ip: data.ip.map(|s| s.to_string()),
+            reason: data.reason.to_string(),
+            banned_by: data.banned_by,
+            expires_at: data.expires_at,
+            created_at: now,
+        })
+    }
+
+    async fn get(&self, id: Uuid) -> DbResult<Option<BanRecord>> {
+        let row = sqlx::query(
+            "SELECT id, user_id, ip, reason, banned_by, expires_at, created_at
+             FROM bans WHERE id = $1",
+        )
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        row.map(|r| row_to_ban(&r)).transpose()
+    }
+
+    async fn list(&self, nur_aktive: bool) -> DbResult<Vec<BanRecord>> {
+        let rows = sqlx::query(
+            "SELECT id, user_id, ip, reason, banned_by, expires_at, created_at
+             FROM bans
+             WHERE NOT $1 OR expires_at IS NULL OR expires_at > now()
+             ORDER BY created_at DESC",
+        )
+        .bind(nur_aktive)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.iter().map(row_to_ban).collect()
+    }
+
+    async fn remove(&self, id: Uuid) -> DbResult<bool> {
+        let affected = sqlx::query("DELETE FROM bans WHERE id = $1")
+            .bind(id)
+            .execute(&self.pool)
+            .await?
+            .rows_affected();
+        Ok(affected > 0)
+    }
+
+    async fn is_banned(
+        &self,
+        user_id: Option<Uuid>,
+        ip: Option<&str>,
+    ) -> DbResult<Option<BanRecord>> {
+        // Prueft ob user_id ODER ip gebannt ist (aktive Bans); ein Vergleich
+        // mit NULL ist nie wahr
+        let row = sqlx::query(
+            "SELECT id, user_id, ip, reason, banned_by, expires_at, created_at
+             FROM bans
+             WHERE (expires_at IS NULL OR expires_at > now())
+               AND (user_id = $1 OR ip = $2)
+             LIMIT 1",
+        )
+        .bind(user_id)
+        .bind(ip)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        row.map(|r| row_to_ban(&r)).transpose()
+    }
+
+    async fn cleanup_expired(&self) -> DbResult<u64> {
+        let affected =
+            sqlx::query("DELETE FROM bans WHERE expires_at IS NOT NULL AND expires_at <= now()")
+                .execute(&self.pool)
+                .await?
+                .rows_affected();
+        Ok(affected)
+    }
+}
+
+fn row_to_ban(row: &sqlx::postgres::PgRow) -> DbResult<BanRecord> {
+    Ok(BanRecord {
+        id: row.try_get("id")?,
+        user_id: row.try_get("user_id")?,
+        ip: row.try_get("ip")?,
+        reason: row.try_get("reason")?,
+        banned_by: row.try_get("banned_by")?,
+        expires_at: row.try_get("expires_at")?,
+        created_at: row.try_get("created_at")?,
+    })
+}
@@ -0,0 +1,299 @@
+//! PostgreSQL-Implementierung des ChannelTemplateRepository
+
+use sqlx::types::Json;
+use sqlx::{Postgres, Row, Transaction};
+use uuid::Uuid;
+
+use crate::error::DbError;
+use crate::models::{
+    KanalRecord, KanalVorlageRecord, KanalbaumGrenzen, NeueKanalVorlage, VorlagenKnoten,
+    VorlagenZiel,
+};
+use crate::postgres::permissions_repo::wert_zu_spalten;
+use crate::postgres::pool::{jetzt, PostgresDb};
+use crate::repository::{ChannelRepository, ChannelTemplateRepository, DbResult};
+
+impl ChannelTemplateRepository for PostgresDb {
+    async fn create(&self, data: NeueKanalVorlage<'_>) -> DbResult<KanalVorlageRecord> {
+        if data.name.trim().is_empty() {
+            return Err(DbError::UngueltigeDaten(
+                "Vorlagenname darf nicht leer sein".into(),
+            ));
+        }
+        data.root.pruefen().map_err(DbError::UngueltigeDaten)?;
+
+        let id = Uuid::new_v4();
+        let now = jetzt();
+
+        sqlx::query(
+            "INSERT INTO channel_templates
+             (id, name, description, definition_json, builtin, created_by, created_at)
+             VALUES ($1, $2, $3, $4, FALSE, $5, $6)",
+        )
+        .bind(id)
+        .bind(data.name)
+        .bind(data.description)
+        .bind(Json(&data.root))
+        .bind(data.created_by)
+        .bind(now)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| {
+            let msg = e.to_string();
+            if msg.contains("unique") {
+                DbError::Eindeutigkeit(format!("Vorlage '{}' existiert bereits", data.name))
+            } else {
+                DbError::Sqlx(e)
+            }
+        })?;
+
+        Ok(KanalVorlageRecord {
+            id,
+            name: data.name.to_string(),
+            description: data.description.map(|s| s.to_string()),
+            root: data.root,
+            builtin: false,
+            created_by: data.created_by,
+            created_at: now,
+        })
+    }
+
+    async fn get_by_id(&self, id: Uuid) -> DbResult<Option<KanalVorlageRecord>> {
+        let row = sqlx::query(
+            "SELECT id, name, description, definition_json, builtin, created_by, created_at
+             FROM channel_templates WHERE id = $1",
+        )
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        row.map(|r| row_to_vorlage(&r)).transpose()
+    }
+
+    async fn list(&self) -> DbResult<Vec<KanalVorlageRecord>> {
+        let rows = sqlx::query(
+            "SELECT id, name, description, definition_json, builtin, created_by, created_at
+             FROM channel_templates ORDER BY builtin DESC, name",
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.iter().map(row_to_vorlage).collect()
+    }
+
+    async fn delete(&self, id: Uuid) -> DbResult<bool> {
+        let builtin: Option<bool> =
+            sqlx::query_scalar("SELECT builtin FROM channel_templates WHERE id = $1")
+                .bind(id)
+                .fetch_optional(&self.pool)
+                .await?;
+
+        match builtin {
+            None => Ok(false),
+            Some(true) => Err(DbError::UngueltigeDaten(
+                "Mitgelieferte Vorlagen koennen nicht geloescht werden".into(),
+            )),
+            Some(false) => {
+                let affected = sqlx::query("DELETE FROM channel_templates WHERE id = $1")
+                    .bind(id)
+                    .execute(&self.pool)
+                    .await?
+                    .rows_affected();
+                Ok(affected > 0)
+            }
+        }
+    }
+
+    async fn instantiate(
+        &self,
+        vorlage_id: Uuid,
+        parent_id: Option<Uuid>,
+        name_prefix: Option<&str>,
+        grenzen: KanalbaumGrenzen,
+    ) -> DbResult<Vec<KanalRecord>> {
+        let vorlage = ChannelTemplateRepository::get_by_id(self, vorlage_id)
+            .await?
+            .ok_or_else(|| DbError::nicht_gefunden(format!("Kanal-Vorlage {vorlage_id}")))?;
+
+        let mut root = vorlage.root;
+        if let Some(prefix) = name_prefix.filter(|p| !p.is_empty()) {
+            root.name = format!("{prefix}{}", root.name);
+        }
+        root.pruefen().map_err(DbError::UngueltigeDaten)?;
+
+        // Alles in einer Transaktion: bei jedem Fehler wird `tx` ohne
+        // commit verworfen und saemtliche Inserts werden zurueckgerollt.
+        let mut tx = self.pool.begin().await?;
+
+        let basis_tiefe = match parent_id {
+            Some(pid) => eltern_tiefe(&mut tx, pid).await?,
+            None => 0,
+        };
+        let neue_tiefe = basis_tiefe + root.tiefe();
+        if grenzen.max_tiefe > 0 && neue_tiefe > grenzen.max_tiefe {
+            return Err(DbError::UngueltigeDaten(format!(
+                "Verschachtelungstiefe {neue_tiefe} ueberschreitet das Limit von {}",
+                grenzen.max_tiefe
+            )));
+        }
+
+        let vorhanden: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM channels")
+            .fetch_one(&mut *tx)
+            .await?;
+        let gesamt = vorhanden as u64 + root.anzahl_kanaele() as u64;
+        if grenzen.max_kanaele > 0 && gesamt > grenzen.max_kanaele as u64 {
+            return Err(DbError::UngueltigeDaten(format!(
+                "Kanal-Limit ueberschritten: {gesamt} > {}",
+                grenzen.max_kanaele
+            )));
+        }
+
+        let ids = teilbaum_anlegen(&mut tx, &root, parent_id, 0).await?;
+
+        tx.commit().await?;
+
+        let mut kanaele = Vec::with_capacity(ids.len());
+        for id in ids {
+            let kanal = ChannelRepository::get_by_id(self, id)
+                .await?
+                .ok_or_else(|| DbError::intern("Kanal nach Instanziierung nicht gefunden"))?;
+            kanaele.push(kanal);
+        }
+        Ok(kanaele)
+    }
+}
+
+/// Legt einen Kanalbaum innerhalb der Transaktion an
+///
+/// Pre-Order: Eltern werden immer vor ihren Kindern angelegt. Gibt die IDs
+/// in dieser Reihenfolge zurueck, der Wurzelkanal zuerst.
+pub(crate) async fn teilbaum_anlegen(
+    tx: &mut Transaction<'_, Postgres>,
+    root: &VorlagenKnoten,
+    parent_id: Option<Uuid>,
+    sort_order: i64,
+) -> DbResult<Vec<Uuid>> {
+    let mut ids = Vec::with_capacity(root.anzahl_kanaele());
+    let mut stapel: Vec<(&VorlagenKnoten, Option<Uuid>, i64)> = vec![(root, parent_id, sort_order)];
+    while let Some((knoten, eltern, sort_order)) = stapel.pop() {
+        let id = kanal_anlegen(tx, knoten, eltern, sort_order).await?;
+        ids.push(id);
+        for (i, kind) in knoten.children.iter().enumerate().rev() {
+            stapel.push((kind, Some(id), i as i64));
+        }
+    }
+    Ok(ids)
+}
+
+/// Tiefe eines bestehenden Kanals (Kanal auf Root-Ebene = 1)
+async fn eltern_tiefe(tx: &mut Transaction<'_, Postgres>, kanal_id: Uuid) -> DbResult<u32> {
+    let mut tiefe = 0u32;
+    let mut aktuell = Some(kanal_id);
+    while let Some(id) = aktuell {
+        let eltern: Option<Option<Uuid>> =
+            sqlx::query_scalar("SELECT parent_id FROM channels WHERE id = $1")
+                .bind(id)
+                .fetch_optional(&mut **tx)
+                .await?;
+        let Some(eltern) = eltern else {
+            return Err(DbError::nicht_gefunden(format!("Kanal {id}")));
+        };
+        tiefe += 1;
+        // Schutz gegen zyklische parent_id-Ketten
+        if tiefe > 1024 {
+            return Err(DbError::intern("Zyklus in der Kanal-Hierarchie"));
+        }
+        aktuell = eltern;
+    }
+    Ok(tiefe)
+}
+
+/// Legt einen einzelnen Kanal samt Berechtigungen innerhalb der Transaktion an
+async fn kanal_anlegen(
+    tx: &mut Transaction<'_, Postgres>,
+    knoten: &VorlagenKnoten,
+    parent_id: Option<Uuid>,
+    sort_order: i64,
+) -> DbResult<Uuid> {
+    let id = Uuid::new_v4();
+
+    sqlx::query(
+        "INSERT INTO channels
+         (id, name, parent_id, topic, password_hash, max_clients, is_default, sort_order,
+          channel_type, codec_profile, created_at)
+         VALUES ($1, $2, $3, $4, NULL, $5, FALSE, $6, $7, $8, $9)",
+    )
+    .bind(id)
+    .bind(knoten.name.trim())
+    .bind(parent_id)
+    .bind(knoten.topic.as_deref())
+    .bind(knoten.max_clients)
+    .bind(sort_order)
+    .bind(knoten.channel_type.als_str())
+    .bind(knoten.codec_profile.as_deref())
+    .bind(jetzt())
+    .execute(&mut **tx)
+    .await?;
+
+    for b in &knoten.permissions {
+        let (target_type, target_id) = match &b.target {
+            VorlagenZiel::ChannelDefault => ("channel_default", id),
+            VorlagenZiel::ChannelGroup(name) => (
+                "channel_group",
+                gruppe_aufloesen(tx, "channel_groups", name).await?,
+            ),
+            VorlagenZiel::ServerGroup(name) => (
+                "server_group",
+                gruppe_aufloesen(tx, "server_groups", name).await?,
+            ),
+        };
+        let (value_type, tri_state, int_limit, scope) = wert_zu_spalten(&b.wert);
+
+        sqlx::query(
+            "INSERT INTO permissions
+               (id, target_type, target_id, permission_key, value_type, tri_state, int_limit, scope_json, channel_id)
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)",
+        )
+        .bind(Uuid::new_v4())
+        .bind(target_type)
+        .bind(target_id)
+        .bind(&b.permission_key)
+        .bind(value_type)
+        .bind(tri_state)
+        .bind(int_limit)
+        .bind(scope.map(Json))
+        .bind(id)
+        .execute(&mut **tx)
+        .await?;
+    }
+
+    Ok(id)
+}
+
+/// Loest einen Gruppennamen zur ID auf (`tabelle` ist eine feste Konstante)
+async fn gruppe_aufloesen(
+    tx: &mut Transaction<'_, Postgres>,
+    tabelle: &'static str,
+    name: &str,
+) -> DbResult<Uuid> {
+    let sql = format!("SELECT id FROM {tabelle} WHERE name = $1");
+    let id: Option<Uuid> = sqlx::query_scalar(&sql)
+        .bind(name)
+        .fetch_optional(&mut **tx)
+        .await?;
+    id.ok_or_else(|| DbError::UngueltigeDaten(format!("Gruppe '{name}' existiert nicht")))
+}
+
+fn row_to_vorlage(row: &sqlx::postgres::PgRow) -> DbResult<KanalVorlageRecord> {
+    let Json(root): Json<VorlagenKnoten> = row.try_get("definition_json")?;
+
+    Ok(KanalVorlageRecord {
+        id: row.try_get("id")?,
+        name: row.try_get("name")?,
+        description: row.try_get("description")?,
+        root,
+        builtin: row.try_get("builtin")?,
+        created_by: row.try_get("created_by")?,
+        created_at: row.try_get("created_at")?,
+    })
+}
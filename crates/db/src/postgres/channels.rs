@@ -0,0 +1,226 @@
+//! PostgreSQL-Implementierung des ChannelRepository
+
+use sqlx::{Postgres, QueryBuilder, Row};
+use uuid::Uuid;
+
+use crate::error::DbError;
+use crate::models::{KanalFilter, KanalRecord, KanalTyp, KanalUpdate, NeuerKanal};
+use crate::postgres::pool::{jetzt, PostgresDb};
+use crate::repository::{ChannelRepository, DbResult};
+
+impl ChannelRepository for PostgresDb {
+    async fn create(&self, data: NeuerKanal<'_>) -> DbResult<KanalRecord> {
+        let id = Uuid::new_v4();
+        let now = jetzt();
+
+        sqlx::query(
+            "INSERT INTO channels
+             (id, name, parent_id, topic, password_hash, max_clients, is_default, sort_order, channel_type, created_at)
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)",
+        )
+        .bind(id)
+        .bind(data.name)
+        .bind(data.parent_id)
+        .bind(data.topic)
+        .bind(data.password_hash)
+        .bind(data.max_clients)
+        .bind(data.is_default)
+        .bind(data.sort_order)
+        .bind(data.channel_type.als_str())
+        .bind(now)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(KanalRecord {
+            id,
+            name: data.name.to_string(),
+            parent_id: data.parent_id,
+            topic: data.topic.map(|s| s.to_string()),
+            password_hash: data.password_hash.map(|s| s.to_string()),
+            max_clients: data.max_clients,
+            is_default: data.is_default,
+            sort_order: data.sort_order,
+            channel_type: data.channel_type,
+            codec_profile: None,
+            slow_mode_secs: 0,
+            edit_window_secs: None,
+            join_by_approval: false,
+            created_at: now,
+        })
+    }
+
+    async fn get_by_id(&self, id: Uuid) -> DbResult<Option<KanalRecord>> {
+        let row = sqlx::query(
+            "SELECT id, name, parent_id, topic, password_hash, max_clients,
+                    is_default, sort_order, channel_type, codec_profile, slow_mode_secs, edit_window_secs, join_by_approval, created_at
+             FROM channels WHERE id = $1",
+        )
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        row.map(|r| row_to_kanal(&r)).transpose()
+    }
+
+    async fn list(&self) -> DbResult<Vec<KanalRecord>> {
+        let rows = sqlx::query(
+            "SELECT id, name, parent_id, topic, password_hash, max_clients,
+                    is_default, sort_order, channel_type, codec_profile, slow_mode_secs, edit_window_secs, join_by_approval, created_at
+             FROM channels ORDER BY sort_order, name",
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.iter().map(row_to_kanal).collect()
+    }
+
+    async fn list_filtered(&self, filter: KanalFilter) -> DbResult<Vec<KanalRecord>> {
+        // LIMIT NULL = ohne Begrenzung
+        let rows = sqlx::query(
+            "SELECT id, name, parent_id, topic, password_hash, max_clients,
+                    is_default, sort_order, channel_type, codec_profile, slow_mode_secs, edit_window_secs, join_by_approval, created_at
+             FROM channels
+             WHERE ($1::TEXT IS NULL OR name ILIKE $1 ESCAPE '\\')
+             ORDER BY sort_order, name, id
+             LIMIT $2 OFFSET $3",
+        )
+        .bind(filter.name_muster())
+        .bind(filter.limit)
+        .bind(filter.offset.unwrap_or(0))
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.iter().map(row_to_kanal).collect()
+    }
+
+    async fn count_filtered(&self, filter: KanalFilter) -> DbResult<i64> {
+        let row = sqlx::query(
+            "SELECT COUNT(*) AS cnt FROM channels
+             WHERE ($1::TEXT IS NULL OR name ILIKE $1 ESCAPE '\\')",
+        )
+        .bind(filter.name_muster())
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(row.try_get("cnt")?)
+    }
+
+    async fn update(&self, id: Uuid, data: KanalUpdate) -> DbResult<KanalRecord> {
+        if data.name.is_none()
+            && data.parent_id.is_none()
+            && data.topic.is_none()
+            && data.password_hash.is_none()
+            && data.max_clients.is_none()
+            && data.is_default.is_none()
+            && data.sort_order.is_none()
+            && data.slow_mode_secs.is_none()
+            && data.edit_window_secs.is_none()
+            && data.join_by_approval.is_none()
+        {
+            return self
+                .get_by_id(id)
+                .await?
+                .ok_or_else(|| DbError::nicht_gefunden(format!("Kanal {id}")));
+        }
+
+        let mut q = QueryBuilder::<Postgres>::new("UPDATE channels SET ");
+        let mut sets = q.separated(", ");
+        if let Some(v) = data.name {
+            sets.push("name = ").push_bind_unseparated(v);
+        }
+        if let Some(v) = data.parent_id {
+            sets.push("parent_id = ").push_bind_unseparated(v);
+        }
+        if let Some(v) = data.topic {
+            sets.push("topic = ").push_bind_unseparated(v);
+        }
+        if let Some(v) = data.password_hash {
+            sets.push("password_hash = ").push_bind_unseparated(v);
+        }
+        if let Some(v) = data.max_clients {
+            sets.push("max_clients = ").push_bind_unseparated(v);
+        }
+        if let Some(v) = data.is_default {
+            sets.push("is_default = ").push_bind_unseparated(v);
+        }
+        if let Some(v) = data.sort_order {
+            sets.push("sort_order = ").push_bind_unseparated(v);
+        }
+        if let Some(v) = data.slow_mode_secs {
+            sets.push("slow_mode_secs = ").push_bind_unseparated(v);
+        }
+        if let Some(v) = data.edit_window_secs {
+            sets.push("edit_window_secs = ").push_bind_unseparated(v);
+        }
+        if let Some(v) = data.join_by_approval {
+            sets.push("join_by_approval = ").push_bind_unseparated(v);
+        }
+        q.push(" WHERE id = ").push_bind(id);
+
+        let affected = q.build().execute(&self.pool).await?.rows_affected();
+        if affected == 0 {
+            return Err(DbError::nicht_gefunden(format!("Kanal {id}")));
+        }
+
+        self.get_by_id(id)
+            .await?
+            .ok_or_else(|| DbError::intern("Kanal nach Update nicht gefunden"))
+    }
+
+    async fn delete(&self, id: Uuid) -> DbResult<bool> {
+        let affected = sqlx::query("DELETE FROM channels WHERE id = $1")
+            .bind(id)
+            .execute(&self.pool)
+            .await?
+            .rows_affected();
+        Ok(affected > 0)
+    }
+
+    async fn get_children(&self, parent_id: Uuid) -> DbResult<Vec<KanalRecord>> {
+        let rows = sqlx::query(
+            "SELECT id, name, parent_id, topic, password_hash, max_clients,
+                    is_default, sort_order, channel_type, codec_profile, slow_mode_secs, edit_window_secs, join_by_approval, created_at
+             FROM channels WHERE parent_id = $1
+             ORDER BY sort_order, name",
+        )
+        .bind(parent_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.iter().map(row_to_kanal).collect()
+    }
+
+    async fn get_default(&self) -> DbResult<Option<KanalRecord>> {
+        let row = sqlx::query(
+            "SELECT id, name, parent_id, topic, password_hash, max_clients,
+                    is_default, sort_order, channel_type, codec_profile, slow_mode_secs, edit_window_secs, join_by_approval, created_at
+             FROM channels WHERE is_default LIMIT 1",
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        row.map(|r| row_to_kanal(&r)).transpose()
+    }
+}
+
+pub(crate) fn row_to_kanal(row: &sqlx::postgres::PgRow) -> DbResult<KanalRecord> {
+    let typ_str: String = row.try_get("channel_type")?;
+    let channel_type = typ_str.parse::<KanalTyp>().map_err(DbError::intern)?;
+
+    Ok(KanalRecord {
+        id: row.try_get("id")?,
+        name: row.try_get("name")?,
+        parent_id: row.try_get("parent_id")?,
+        topic: row.try_get("topic")?,
+        password_hash: row.try_get("password_hash")?,
+        max_clients: row.try_get("max_clients")?,
+        is_default: row.try_get("is_default")?,
+        sort_order: row.try_get("sort_order")?,
+        channel_type,
+        codec_profile: row.try_get("codec_profile")?,
+        slow_mode_secs: row.try_get("slow_mode_secs")?,
+        edit_window_secs: row.try_get("edit_window_secs")?,
+        join_by_approval: row.try_get("join_by_approval")?,
+        created_at: row.try_get("created_at")?,
+    })
+}
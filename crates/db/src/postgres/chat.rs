@@ -0,0 +1,156 @@
+//! PostgreSQL-Implementierung des ChatMessageRepository
+
+use sqlx::Row;
+use uuid::Uuid;
+
+use crate::error::DbError;
+use crate::models::{
+    ChatNachrichtRecord, NachrichtenFilter, NachrichtenSuche, NachrichtenTyp, NeueNachricht,
+};
+use crate::postgres::pool::{jetzt, PostgresDb};
+use crate::repository::{ChatMessageRepository, DbResult};
+
+impl ChatMessageRepository for PostgresDb {
+    async fn create(&self, data: NeueNachricht<'_>) -> DbResult<ChatNachrichtRecord> {
+        let id = Uuid::new_v4();
+        let now = jetzt();
+
+        sqlx::query(
+            "INSERT INTO chat_messages
+             (id, channel_id, sender_id, content, message_type, reply_to, created_at)
+             VALUES ($1, $2, $3, $4, $5, $6, $7)",
+        )
+        .bind(id)
+        .bind(data.channel_id)
+        .bind(data.sender_id)
+        .bind(data.content)
+        .bind(data.message_type.als_str())
+        .bind(data.reply_to)
+        .bind(now)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(ChatNachrichtRecord {
+            id,
+            channel_id: data.channel_id,
+            sender_id: data.sender_id,
+            content: data.content.to_string(),
+            message_type: data.message_type,
+            reply_to: data.reply_to,
+            created_at: now,
+            edited_at: None,
+            deleted_at: None,
+            edit_count: 0,
+        })
+    }
+
+    async fn get_by_id(&self, id: Uuid) -> DbResult<Option<ChatNachrichtRecord>> {
+        let row = sqlx::query(
+            "SELECT id, channel_id, sender_id, content, message_type,
+                    reply_to, created_at, edited_at, deleted_at, edit_count
+             FROM chat_messages WHERE id = $1",
+        )
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        row.map(|r| row_to_nachricht(&r)).transpose()
+    }
+
+    async fn get_history(&self, filter: NachrichtenFilter) -> DbResult<Vec<ChatNachrichtRecord>> {
+        let rows = sqlx::query(
+            "SELECT id, channel_id, sender_id, content, message_type,
+                    reply_to, created_at, edited_at, deleted_at, edit_count
+             FROM chat_messages
+             WHERE channel_id = $1
+               AND ($2::timestamptz IS NULL OR created_at < $2)
+               AND deleted_at IS NULL
+             ORDER BY created_at DESC, seq DESC
+             LIMIT $3",
+        )
+        .bind(filter.channel_id)
+        .bind(filter.before)
+        .bind(filter.limit.unwrap_or(50))
+        .fetch_all(&self.pool)
+        .await?;
+
+        // Chronologisch sortieren (aelteste zuerst)
+        let mut records: Vec<ChatNachrichtRecord> =
+            rows.iter().map(row_to_nachricht).collect::<DbResult<_>>()?;
+        records.reverse();
+        Ok(records)
+    }
+
+    async fn update_content(&self, id: Uuid, new_content: &str) -> DbResult<ChatNachrichtRecord> {
+        let row = sqlx::query(
+            "UPDATE chat_messages SET content = $1, edited_at = $2, edit_count = edit_count + 1
+             WHERE id = $3 AND deleted_at IS NULL
+             RETURNING id, channel_id, sender_id, content, message_type,
+                       reply_to, created_at, edited_at, deleted_at, edit_count",
+        )
+        .bind(new_content)
+        .bind(jetzt())
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        match row {
+            Some(r) => row_to_nachricht(&r),
+            None => Err(DbError::nicht_gefunden(format!("Nachricht {id}"))),
+        }
+    }
+
+    async fn soft_delete(&self, id: Uuid) -> DbResult<bool> {
+        let affected = sqlx::query(
+            "UPDATE chat_messages SET deleted_at = $1 WHERE id = $2 AND deleted_at IS NULL",
+        )
+        .bind(jetzt())
+        .bind(id)
+        .execute(&self.pool)
+        .await?
+        .rows_affected();
+
+        Ok(affected > 0)
+    }
+
+    async fn search(&self, suche: NachrichtenSuche<'_>) -> DbResult<Vec<ChatNachrichtRecord>> {
+        let rows = sqlx::query(
+            "SELECT id, channel_id, sender_id, content, message_type,
+                    reply_to, created_at, edited_at, deleted_at, edit_count
+             FROM chat_messages
+             WHERE channel_id = $1 AND content ILIKE $2 ESCAPE '\\' AND deleted_at IS NULL
+               AND ($3::timestamptz IS NULL OR created_at < $3
+                    OR (created_at = $3 AND seq < (SELECT seq FROM chat_messages WHERE id = $6)))
+             ORDER BY created_at DESC, seq DESC
+             LIMIT $4 OFFSET $5",
+        )
+        .bind(suche.channel_id)
+        .bind(suche.like_muster())
+        .bind(suche.before)
+        .bind(suche.limit)
+        .bind(suche.offset)
+        .bind(suche.before_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.iter().map(row_to_nachricht).collect()
+    }
+}
+
+pub(crate) fn row_to_nachricht(row: &sqlx::postgres::PgRow) -> DbResult<ChatNachrichtRecord> {
+    let typ_str: String = row.try_get("message_type")?;
+    let message_type = typ_str.parse::<NachrichtenTyp>().map_err(DbError::intern)?;
+
+    Ok(ChatNachrichtRecord {
+        id: row.try_get("id")?,
+        channel_id: row.try_get("channel_id")?,
+        sender_id: row.try_get("sender_id")?,
+        content: row.try_get("content")?,
+        message_type,
+        reply_to: row.try_get("reply_to")?,
+        created_at: row.try_get("created_at")?,
+        edited_at: row.try_get("edited_at")?,
+        deleted_at: row.try_get("deleted_at")?,
+        edit_count: row.try_get("edit_count")?,
+    })
+}
@@ -0,0 +1,370 @@
+//! PostgreSQL-Implementierung des FileRepository
+
+use chrono::Utc;
+use sqlx::{Postgres, QueryBuilder, Row};
+use uuid::Uuid;
+
+use crate::models::{
+    DateiKontingentRecord, DateiRecord, DateiZugriffFilter, DateiZugriffRecord, NeueDatei,
+    NeuerDateiZugriff, NeuerUpload, UploadRecord,
+};
+use crate::postgres::pool::{jetzt, PostgresDb};
+use crate::repository::{DbResult, FileRepository};
+
+impl FileRepository for PostgresDb {
+    async fn create(&self, data: NeueDatei<'_>) -> DbResult<DateiRecord> {
+        let id = Uuid::new_v4();
+        let now = jetzt();
+
+        sqlx::query(
+            "INSERT INTO files
+             (id, channel_id, uploader_id, filename, mime_type, size_bytes, storage_path, checksum, created_at)
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)",
+        )
+        .bind(id)
+        .bind(data.channel_id)
+        .bind(data.uploader_id)
+        .bind(data.filename)
+        .bind(data.mime_type)
+        .bind(data.size_bytes)
+        .bind(data.storage_path)
+        .bind(data.checksum)
+        .bind(now)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(DateiRecord {
+            id,
+            channel_id: data.channel_id,
+            uploader_id: data.uploader_id,
+            filename: data.filename.to_string(),
+            mime_type: data.mime_type.to_string(),
+            size_bytes: data.size_bytes,
+            storage_path: data.storage_path.to_string(),
+            checksum: data.checksum.to_string(),
+            created_at: now,
+            deleted_at: None,
+        })
+    }
+
+    async fn get_by_id(&self, id: Uuid) -> DbResult<Option<DateiRecord>> {
+        let row = sqlx::query(
+            "SELECT id, channel_id, uploader_id, filename, mime_type,
+                    size_bytes, storage_path, checksum, created_at, deleted_at
+             FROM files WHERE id = $1",
+        )
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        row.map(|r| row_to_datei(&r)).transpose()
+    }
+
+    async fn list_by_channel(&self, channel_id: Uuid) -> DbResult<Vec<DateiRecord>> {
+        let rows = sqlx::query(
+            "SELECT id, channel_id, uploader_id, filename, mime_type,
+                    size_bytes, storage_path, checksum, created_at, deleted_at
+             FROM files
+             WHERE channel_id = $1 AND deleted_at IS NULL
+             ORDER BY created_at DESC, seq DESC",
+        )
+        .bind(channel_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.iter().map(row_to_datei).collect()
+    }
+
+    async fn soft_delete(&self, id: Uuid) -> DbResult<bool> {
+        let affected =
+            sqlx::query("UPDATE files SET deleted_at = $1 WHERE id = $2 AND deleted_at IS NULL")
+                .bind(jetzt())
+                .bind(id)
+                .execute(&self.pool)
+                .await?
+                .rows_affected();
+
+        Ok(affected > 0)
+    }
+
+    async fn get_quota(&self, group_id: &str) -> DbResult<DateiKontingentRecord> {
+        let row = sqlx::query(
+            "SELECT group_id, max_file_size, max_total_storage, current_usage
+             FROM file_quotas WHERE group_id = $1",
+        )
+        .bind(group_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        if let Some(r) = row {
+            Ok(DateiKontingentRecord {
+                group_id: r.try_get("group_id")?,
+                max_file_size: r.try_get("max_file_size")?,
+                max_total_storage: r.try_get("max_total_storage")?,
+                current_usage: r.try_get("current_usage")?,
+            })
+        } else {
+            // Standard-Kontingent zurueckgeben wenn noch keines konfiguriert
+            Ok(DateiKontingentRecord {
+                group_id: group_id.to_string(),
+                max_file_size: 10 * 1024 * 1024,       // 10 MB
+                max_total_storage: 1024 * 1024 * 1024, // 1 GB
+                current_usage: 0,
+            })
+        }
+    }
+
+    async fn increment_usage(&self, group_id: &str, bytes: i64) -> DbResult<()> {
+        // Upsert: Wenn kein Eintrag vorhanden, Standard-Werte einfuegen
+        sqlx::query(
+            "INSERT INTO file_quotas (group_id, max_file_size, max_total_storage, current_usage)
+             VALUES ($1, 10485760, 1073741824, $2)
+             ON CONFLICT(group_id) DO UPDATE
+             SET current_usage = file_quotas.current_usage + excluded.current_usage",
+        )
+        .bind(group_id)
+        .bind(bytes)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn decrement_usage(&self, group_id: &str, bytes: i64) -> DbResult<()> {
+        sqlx::query(
+            "UPDATE file_quotas
+             SET current_usage = GREATEST(0, current_usage - $1)
+             WHERE group_id = $2",
+        )
+        .bind(bytes)
+        .bind(group_id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn log_access(&self, data: NeuerDateiZugriff) -> DbResult<DateiZugriffRecord> {
+        let id = Uuid::new_v4();
+        let now = jetzt();
+
+        sqlx::query(
+            "INSERT INTO file_access_log
+             (id, file_id, user_id, timestamp, bytes_served, completed)
+             VALUES ($1, $2, $3, $4, $5, $6)",
+        )
+        .bind(id)
+        .bind(data.file_id)
+        .bind(data.user_id)
+        .bind(now)
+        .bind(data.bytes_served)
+        .bind(data.completed)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(DateiZugriffRecord {
+            id,
+            file_id: data.file_id,
+            user_id: data.user_id,
+            timestamp: now,
+            bytes_served: data.bytes_served,
+            completed: data.completed,
+        })
+    }
+
+    async fn list_access(&self, filter: DateiZugriffFilter) -> DbResult<Vec<DateiZugriffRecord>> {
+        let mut q = QueryBuilder::<Postgres>::new(
+            "SELECT id, file_id, user_id, timestamp, bytes_served, completed
+             FROM file_access_log",
+        );
+        zugriff_filter_anhaengen(&mut q, &filter);
+        q.push(" ORDER BY timestamp DESC, seq DESC");
+        if let Some(limit) = filter.limit {
+            q.push(" LIMIT ").push_bind(limit);
+        }
+        if let Some(offset) = filter.offset {
+            q.push(" OFFSET ").push_bind(offset);
+        }
+
+        let rows = q.build().fetch_all(&self.pool).await?;
+        rows.iter().map(row_to_zugriff).collect()
+    }
+
+    async fn count_access(&self, filter: DateiZugriffFilter) -> DbResult<i64> {
+        let mut q = QueryBuilder::<Postgres>::new("SELECT COUNT(*) AS cnt FROM file_access_log");
+        zugriff_filter_anhaengen(&mut q, &filter);
+
+        let row = q.build().fetch_one(&self.pool).await?;
+        Ok(row.try_get("cnt")?)
+    }
+
+    async fn count_access_by_file(&self, file_ids: &[Uuid]) -> DbResult<Vec<(Uuid, i64)>> {
+        if file_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+        let rows = sqlx::query(
+            "SELECT file_id, COUNT(*) AS cnt FROM file_access_log
+             WHERE file_id = ANY($1)
+             GROUP BY file_id",
+        )
+        .bind(file_ids)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.iter()
+            .map(|row| Ok((row.try_get("file_id")?, row.try_get("cnt")?)))
+            .collect()
+    }
+
+    async fn purge_access_before(&self, before: chrono::DateTime<Utc>) -> DbResult<u64> {
+        let affected = sqlx::query("DELETE FROM file_access_log WHERE timestamp < $1")
+            .bind(before)
+            .execute(&self.pool)
+            .await?
+            .rows_affected();
+
+        Ok(affected)
+    }
+
+    async fn create_upload(&self, data: NeuerUpload<'_>) -> DbResult<UploadRecord> {
+        let id = Uuid::new_v4();
+        let now = jetzt();
+
+        sqlx::query(
+            "INSERT INTO file_uploads
+             (id, channel_id, uploader_id, filename, mime_type, size_bytes, checksum,
+              created_at, expires_at)
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)",
+        )
+        .bind(id)
+        .bind(data.channel_id)
+        .bind(data.uploader_id)
+        .bind(data.filename)
+        .bind(data.mime_type)
+        .bind(data.size_bytes)
+        .bind(data.checksum)
+        .bind(now)
+        .bind(data.expires_at)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(UploadRecord {
+            id,
+            channel_id: data.channel_id,
+            uploader_id: data.uploader_id,
+            filename: data.filename.to_string(),
+            mime_type: data.mime_type.to_string(),
+            size_bytes: data.size_bytes,
+            checksum: data.checksum.map(str::to_string),
+            created_at: now,
+            expires_at: data.expires_at,
+        })
+    }
+
+    async fn get_upload(&self, id: Uuid) -> DbResult<Option<UploadRecord>> {
+        let row = sqlx::query(
+            "SELECT id, channel_id, uploader_id, filename, mime_type, size_bytes, checksum,
+                    created_at, expires_at
+             FROM file_uploads WHERE id = $1",
+        )
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        row.map(|r| row_to_upload(&r)).transpose()
+    }
+
+    async fn complete_upload(
+        &self,
+        id: Uuid,
+        storage_path: &str,
+        checksum: &str,
+    ) -> DbResult<Option<DateiRecord>> {
+        // Entfernen und Uebernehmen in einer Anweisung: ein zweiter Abschluss
+        // findet keine Zeile mehr
+        let row = sqlx::query(
+            "WITH erledigt AS (DELETE FROM file_uploads WHERE id = $1 RETURNING *)
+             INSERT INTO files
+             (id, channel_id, uploader_id, filename, mime_type, size_bytes, storage_path, checksum, created_at)
+             SELECT id, channel_id, uploader_id, filename, mime_type, size_bytes, $2, $3, $4
+             FROM erledigt
+             RETURNING id, channel_id, uploader_id, filename, mime_type,
+                       size_bytes, storage_path, checksum, created_at, deleted_at",
+        )
+        .bind(id)
+        .bind(storage_path)
+        .bind(checksum)
+        .bind(jetzt())
+        .fetch_optional(&self.pool)
+        .await?;
+
+        row.map(|r| row_to_datei(&r)).transpose()
+    }
+
+    async fn purge_uploads_before(&self, before: chrono::DateTime<Utc>) -> DbResult<u64> {
+        let affected = sqlx::query("DELETE FROM file_uploads WHERE expires_at < $1")
+            .bind(before)
+            .execute(&self.pool)
+            .await?
+            .rows_affected();
+
+        Ok(affected)
+    }
+}
+
+/// Haengt die WHERE-Klausel fuer Zugriffsprotokoll-Abfragen an
+fn zugriff_filter_anhaengen(q: &mut QueryBuilder<'_, Postgres>, filter: &DateiZugriffFilter) {
+    q.push(" WHERE TRUE");
+    if let Some(file_id) = filter.file_id {
+        q.push(" AND file_id = ").push_bind(file_id);
+    }
+    if let Some(user_id) = filter.user_id {
+        q.push(" AND user_id = ").push_bind(user_id);
+    }
+    if let Some(since) = filter.since {
+        q.push(" AND timestamp >= ").push_bind(since);
+    }
+    if let Some(until) = filter.until {
+        q.push(" AND timestamp <= ").push_bind(until);
+    }
+}
+
+fn row_to_zugriff(row: &sqlx::postgres::PgRow) -> DbResult<DateiZugriffRecord> {
+    Ok(DateiZugriffRecord {
+        id: row.try_get("id")?,
+        file_id: row.try_get("file_id")?,
+        user_id: row.try_get("user_id")?,
+        timestamp: row.try_get("timestamp")?,
+        bytes_served: row.try_get("bytes_served")?,
+        completed: row.try_get("completed")?,
+    })
+}
+
+pub(crate) fn row_to_datei(row: &sqlx::postgres::PgRow) -> DbResult<DateiRecord> {
+    Ok(DateiRecord {
+        id: row.try_get("id")?,
+        channel_id: row.try_get("channel_id")?,
+        uploader_id: row.try_get("uploader_id")?,
+        filename: row.try_get("filename")?,
+        mime_type: row.try_get("mime_type")?,
+        size_bytes: row.try_get("size_bytes")?,
+        storage_path: row.try_get("storage_path")?,
+        checksum: row.try_get("checksum")?,
+        created_at: row.try_get("created_at")?,
+        deleted_at: row.try_get("deleted_at")?,
+    })
+}
+
+fn row_to_upload(row: &sqlx::postgres::PgRow) -> DbResult<UploadRecord> {
+    Ok(UploadRecord {
+        id: row.try_get("id")?,
+        channel_id: row.try_get("channel_id")?,
+        uploader_id: row.try_get("uploader_id")?,
+        filename: row.try_get("filename")?,
+        mime_type: row.try_get("mime_type")?,
+        size_bytes: row.try_get("size_bytes")?,
+        checksum: row.try_get("checksum")?,
+        created_at: row.try_get("created_at")?,
+        expires_at: row.try_get("expires_at")?,
+    })
+}
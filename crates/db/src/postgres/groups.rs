@@ -0,0 +1,253 @@
+//! PostgreSQL-Implementierung der Group-Repositories (Server- und Kanal-Gruppen)
+
+use sqlx::Row;
+use uuid::Uuid;
+
+use crate::error::DbError;
+use crate::models::{KanalGruppeRecord, NeueKanalGruppe, NeueServerGruppe, ServerGruppeRecord};
+use crate::postgres::pool::PostgresDb;
+use crate::repository::{ChannelGroupRepository, DbResult, ServerGroupRepository};
+
+// ---------------------------------------------------------------------------
+// ServerGroupRepository
+// ---------------------------------------------------------------------------
+
+impl ServerGroupRepository for PostgresDb {
+    async fn create(&self, data: NeueServerGruppe<'_>) -> DbResult<ServerGruppeRecord> {
+        let id = Uuid::new_v4();
+
+        sqlx::query(
+            "INSERT INTO server_groups (id, name, priority, is_default, permissions)
+             VALUES ($1, $2, $3, $4, '{}')",
+        )
+        .bind(id)
+        .bind(data.name)
+        .bind(data.priority)
+        .bind(data.is_default)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| {
+            let msg = e.to_string();
+            if msg.contains("unique") {
+                DbError::Eindeutigkeit(format!("Server-Gruppe '{}' existiert bereits", data.name))
+            } else {
+                DbError::Sqlx(e)
+            }
+        })?;
+
+        Ok(ServerGruppeRecord {
+            id,
+            name: data.name.to_string(),
+            priority: data.priority,
+            is_default: data.is_default,
+            permissions: serde_json::Value::Object(Default::default()),
+        })
+    }
+
+    async fn get(&self, id: Uuid) -> DbResult<Option<ServerGruppeRecord>> {
+        let row = sqlx::query(
+            "SELECT id, name, priority, is_default, permissions
+             FROM server_groups WHERE id = $1",
+        )
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        row.map(|r| row_to_server_gruppe(&r)).transpose()
+    }
+
+    async fn list(&self) -> DbResult<Vec<ServerGruppeRecord>> {
+        let rows = sqlx::query(
+            "SELECT id, name, priority, is_default, permissions
+             FROM server_groups ORDER BY priority DESC, name",
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.iter().map(row_to_server_gruppe).collect()
+    }
+
+    async fn list_for_user(&self, user_id: Uuid) -> DbResult<Vec<ServerGruppeRecord>> {
+        let rows = sqlx::query(
+            "SELECT sg.id, sg.name, sg.priority, sg.is_default, sg.permissions
+             FROM server_groups sg
+             JOIN user_server_groups usg ON usg.group_id = sg.id
+             WHERE usg.user_id = $1
+             ORDER BY sg.priority DESC",
+        )
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.iter().map(row_to_server_gruppe).collect()
+    }
+
+    async fn add_member(&self, group_id: Uuid, user_id: Uuid) -> DbResult<()> {
+        sqlx::query(
+            "INSERT INTO user_server_groups (user_id, group_id) VALUES ($1, $2)
+             ON CONFLICT DO NOTHING",
+        )
+        .bind(user_id)
+        .bind(group_id)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    async fn remove_member(&self, group_id: Uuid, user_id: Uuid) -> DbResult<bool> {
+        let affected =
+            sqlx::query("DELETE FROM user_server_groups WHERE user_id = $1 AND group_id = $2")
+                .bind(user_id)
+                .bind(group_id)
+                .execute(&self.pool)
+                .await?
+                .rows_affected();
+        Ok(affected > 0)
+    }
+
+    async fn get_default(&self) -> DbResult<Option<ServerGruppeRecord>> {
+        let row = sqlx::query(
+            "SELECT id, name, priority, is_default, permissions
+             FROM server_groups WHERE is_default LIMIT 1",
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        row.map(|r| row_to_server_gruppe(&r)).transpose()
+    }
+
+    async fn delete(&self, id: Uuid) -> DbResult<bool> {
+        let affected = sqlx::query("DELETE FROM server_groups WHERE id = $1")
+            .bind(id)
+            .execute(&self.pool)
+            .await?
+            .rows_affected();
+        Ok(affected > 0)
+    }
+}
+
+fn row_to_server_gruppe(row: &sqlx::postgres::PgRow) -> DbResult<ServerGruppeRecord> {
+    Ok(ServerGruppeRecord {
+        id: row.try_get("id")?,
+        name: row.try_get("name")?,
+        priority: row.try_get("priority")?,
+        is_default: row.try_get("is_default")?,
+        permissions: row.try_get("permissions")?,
+    })
+}
+
+// ---------------------------------------------------------------------------
+// ChannelGroupRepository
+// ---------------------------------------------------------------------------
+
+impl ChannelGroupRepository for PostgresDb {
+    async fn create(&self, data: NeueKanalGruppe<'_>) -> DbResult<KanalGruppeRecord> {
+        let id = Uuid::new_v4();
+
+        sqlx::query("INSERT INTO channel_groups (id, name, permissions) VALUES ($1, $2, '{}')")
+            .bind(id)
+            .bind(data.name)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| {
+                let msg = e.to_string();
+                if msg.contains("unique") {
+                    DbError::Eindeutigkeit(format!(
+                        "Kanal-Gruppe '{}' existiert bereits",
+                        data.name
+                    ))
+                } else {
+                    DbError::Sqlx(e)
+                }
+            })?;
+
+        Ok(KanalGruppeRecord {
+            id,
+            name: data.name.to_string(),
+            permissions: serde_json::Value::Object(Default::default()),
+        })
+    }
+
+    async fn get(&self, id: Uuid) -> DbResult<Option<KanalGruppeRecord>> {
+        let row = sqlx::query("SELECT id, name, permissions FROM channel_groups WHERE id = $1")
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        row.map(|r| row_to_kanal_gruppe(&r)).transpose()
+    }
+
+    async fn list(&self) -> DbResult<Vec<KanalGruppeRecord>> {
+        let rows = sqlx::query("SELECT id, name, permissions FROM channel_groups ORDER BY name")
+            .fetch_all(&self.pool)
+            .await?;
+
+        rows.iter().map(row_to_kanal_gruppe).collect()
+    }
+
+    async fn get_for_user_in_channel(
+        &self,
+        user_id: Uuid,
+        channel_id: Uuid,
+    ) -> DbResult<Option<KanalGruppeRecord>> {
+        let row = sqlx::query(
+            "SELECT cg.id, cg.name, cg.permissions
+             FROM channel_groups cg
+             JOIN user_channel_groups ucg ON ucg.group_id = cg.id
+             WHERE ucg.user_id = $1 AND ucg.channel_id = $2",
+        )
+        .bind(user_id)
+        .bind(channel_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        row.map(|r| row_to_kanal_gruppe(&r)).transpose()
+    }
+
+    async fn set_member_group(
+        &self,
+        user_id: Uuid,
+        channel_id: Uuid,
+        group_id: Uuid,
+    ) -> DbResult<()> {
+        sqlx::query(
+            "INSERT INTO user_channel_groups (user_id, channel_id, group_id)
+             VALUES ($1, $2, $3)
+             ON CONFLICT(user_id, channel_id) DO UPDATE SET group_id = excluded.group_id",
+        )
+        .bind(user_id)
+        .bind(channel_id)
+        .bind(group_id)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    async fn remove_member_group(&self, user_id: Uuid, channel_id: Uuid) -> DbResult<bool> {
+        let affected =
+            sqlx::query("DELETE FROM user_channel_groups WHERE user_id = $1 AND channel_id = $2")
+                .bind(user_id)
+                .bind(channel_id)
+                .execute(&self.pool)
+                .await?
+                .rows_affected();
+        Ok(affected > 0)
+    }
+
+    async fn delete(&self, id: Uuid) -> DbResult<bool> {
+        let affected = sqlx::query("DELETE FROM channel_groups WHERE id = $1")
+            .bind(id)
+            .execute(&self.pool)
+            .await?
+            .rows_affected();
+        Ok(affected > 0)
+    }
+}
+
+fn row_to_kanal_gruppe(row: &sqlx::postgres::PgRow) -> DbResult<KanalGruppeRecord> {
+    Ok(KanalGruppeRecord {
+        id: row.try_get("id")?,
+        name: row.try_get("name")?,
+        permissions: row.try_get("permissions")?,
+    })
+}
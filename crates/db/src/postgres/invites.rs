@@ -0,0 +1,176 @@
+//! PostgreSQL-Implementierung des InviteRepository
+
+use chrono::Utc;
+use sqlx::Row;
+use uuid::Uuid;
+
+use crate::error::DbError;
+use crate::models::{EinladungRecord, NeueEinladung};
+use crate::postgres::pool::{jetzt, PostgresDb};
+use crate::repository::{DbResult, InviteRepository};
+
+impl InviteRepository for PostgresDb {
+    async fn create(&self, data: NeueEinladung<'_>) -> DbResult<EinladungRecord> {
+        let id = Uuid::new_v4();
+        let now = jetzt();
+
+        sqlx::query(
+            "INSERT INTO invites
+               (id, code, channel_id, assigned_group_id, max_uses, used_count, expires_at, created_by, created_at)
+             VALUES ($1, $2, $3, $4, $5, 0, $6, $7, $8)",
+        )
+        .bind(id)
+        .bind(data.code)
+        .bind(data.channel_id)
+        .bind(data.assigned_group_id)
+        .bind(data.max_uses)
+        .bind(data.expires_at)
+        .bind(data.created_by)
+        .bind(now)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| {
+            let msg = e.to_string();
+            if msg.contains("unique") {
+                DbError::Eindeutigkeit(format!("Einladungscode '{}' bereits vergeben", data.code))
+            } else {
+                DbError::Sqlx(e)
+            }
+        })?;
+
+        Ok(EinladungRecord {
+            id,
+            code: data.code.to_string(),
+            channel_id: data.channel_id,
+            assigned_group_id: data.assigned_group_id,
+            max_uses: data.max_uses,
+            used_count: 0,
+            expires_at: data.expires_at,
+            created_by: data.created_by,
+            created_at: now,
+        })
+    }
+
+    async fn get(&self, id: Uuid) -> DbResult<Option<EinladungRecord>> {
+        let row = sqlx::query(
+            "SELECT id, code, channel_id, assigned_group_id, max_uses, used_count,
+                    expires_at, created_by, created_at
+             FROM invites WHERE id = $1",
+        )
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        row.map(|r| row_to_invite(&r)).transpose()
+    }
+
+    async fn get_by_code(&self, code: &str) -> DbResult<Option<EinladungRecord>> {
+        let row = sqlx::query(
+            "SELECT id, code, channel_id, assigned_group_id, max_uses, used_count,
+                    expires_at, created_by, created_at
+             FROM invites WHERE code = $1",
+        )
+        .bind(code)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        row.map(|r| row_to_invite(&r)).transpose()
+    }
+
+    async fn list(&self, created_by: Option<Uuid>) -> DbResult<Vec<EinladungRecord>> {
+        let rows = sqlx::query(
+            "SELECT id, code, channel_id, assigned_group_id, max_uses, used_count,
+                    expires_at, created_by, created_at
+             FROM invites WHERE $1::uuid IS NULL OR created_by = $1
+             ORDER BY created_at DESC",
+        )
+        .bind(created_by)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.iter().map(row_to_invite).collect()
+    }
+
+    async fn use_invite(&self, code: &str) -> DbResult<Option<EinladungRecord>> {
+        // Zeile sperren, damit parallele Einloesungen (auch von anderen
+        // Instanzen) das Limit nicht ueberschreiten
+        let mut tx = self.pool.begin().await?;
+
+        let row = sqlx::query(
+            "SELECT id, code, channel_id, assigned_group_id, max_uses, used_count,
+                    expires_at, created_by, created_at
+             FROM invites WHERE code = $1
+             FOR UPDATE",
+        )
+        .bind(code)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        let row = match row {
+            None => return Ok(None),
+            Some(r) => r,
+        };
+
+        let invite = row_to_invite(&row)?;
+
+        // Ablauf pruefen
+        if let Some(expires) = invite.expires_at {
+            if expires < Utc::now() {
+                tx.rollback().await?;
+                return Err(DbError::EinladungUngueltig);
+            }
+        }
+
+        // Verbrauchslimit pruefen (0 = unbegrenzt)
+        if invite.max_uses > 0 && invite.used_count >= invite.max_uses {
+            tx.rollback().await?;
+            return Err(DbError::EinladungErschoepft);
+        }
+
+        let row = sqlx::query(
+            "UPDATE invites SET used_count = used_count + 1 WHERE code = $1
+             RETURNING id, code, channel_id, assigned_group_id, max_uses, used_count,
+                       expires_at, created_by, created_at",
+        )
+        .bind(code)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        row_to_invite(&row).map(Some)
+    }
+
+    async fn release_invite(&self, code: &str) -> DbResult<bool> {
+        let affected = sqlx::query(
+            "UPDATE invites SET used_count = used_count - 1 WHERE code = $1 AND used_count > 0",
+        )
+        .bind(code)
+        .execute(&self.pool)
+        .await?
+        .rows_affected();
+        Ok(affected > 0)
+    }
+
+    async fn revoke(&self, id: Uuid) -> DbResult<bool> {
+        let affected = sqlx::query("DELETE FROM invites WHERE id = $1")
+            .bind(id)
+            .execute(&self.pool)
+            .await?
+            .rows_affected();
+        Ok(affected > 0)
+    }
+}
+
+fn row_to_invite(row: &sqlx::postgres::PgRow) -> DbResult<EinladungRecord> {
+    Ok(EinladungRecord {
+        id: row.try_get("id")?,
+        code: row.try_get("code")?,
+        channel_id: row.try_get("channel_id")?,
+        assigned_group_id: row.try_get("assigned_group_id")?,
+        max_uses: row.try_get("max_uses")?,
+        used_count: row.try_get("used_count")?,
+        expires_at: row.try_get("expires_at")?,
+        created_by: row.try_get("created_by")?,
+        created_at: row.try_get("created_at")?,
+    })
+}
@@ -0,0 +1,535 @@
+//! PostgreSQL-Implementierung des KontoRepository
+
+use chrono::{DateTime, Utc};
+use sqlx::Row;
+use uuid::Uuid;
+
+use crate::error::DbError;
+use crate::models::{
+    InhaltsBereinigungRecord, InhaltsStapel, KontoDatei, KontoDaten, KontoExportRecord,
+    KontoLoeschAuftrag, KontoLoeschung, KontoNachricht, KontoProfil, NachrichtenRichtlinie,
+    NeueInhaltsBereinigung, NeuerKontoExport, GELOESCHTER_BENUTZER,
+};
+use crate::postgres::audit::row_to_audit;
+use crate::postgres::chat::row_to_nachricht;
+use crate::postgres::files::row_to_datei;
+use crate::postgres::pool::{jetzt, PostgresDb};
+use crate::postgres::users::row_to_benutzer;
+use crate::repository::{DbResult, KontoRepository};
+
+const EXPORT_SPALTEN: &str =
+    "id, user_id, storage_path, size_bytes, created_at, completed_at, expires_at, downloaded_at";
+
+const BEREINIGUNG_SPALTEN: &str =
+    "id, user_id, since, channel_id, requested_by, messages, files, created_at, completed_at";
+
+impl KontoRepository for PostgresDb {
+    async fn daten_sammeln(&self, user_id: Uuid) -> DbResult<Option<KontoDaten>> {
+        // Eine Lese-Transaktion mit festem Snapshot: alle Teile stammen aus
+        // demselben Stand
+        let mut tx = self.pool.begin().await?;
+        sqlx::query("SET TRANSACTION ISOLATION LEVEL REPEATABLE READ, READ ONLY")
+            .execute(&mut *tx)
+            .await?;
+
+        let row = sqlx::query(
+            "SELECT id, username, password_hash, created_at, last_login, is_active, password_changed
+             FROM users WHERE id = $1",
+        )
+        .bind(user_id)
+        .fetch_optional(&mut *tx)
+        .await?;
+        let Some(benutzer) = row.map(|r| row_to_benutzer(&r)).transpose()? else {
+            return Ok(None);
+        };
+
+        let server_gruppen: Vec<String> = sqlx::query_scalar(
+            "SELECT sg.name FROM server_groups sg
+             JOIN user_server_groups usg ON usg.group_id = sg.id
+             WHERE usg.user_id = $1
+             ORDER BY sg.priority DESC, sg.name",
+        )
+        .bind(user_id)
+        .fetch_all(&mut *tx)
+        .await?;
+
+        let nachrichten = sqlx::query(
+            "SELECT m.id, m.channel_id, m.sender_id, m.content, m.message_type,
+                    m.reply_to, m.created_at, m.edited_at, m.deleted_at, m.edit_count,
+                    c.name AS kanal_name
+             FROM chat_messages m
+             JOIN channels c ON c.id = m.channel_id
+             WHERE m.sender_id = $1
+             ORDER BY m.created_at, m.seq",
+        )
+        .bind(user_id)
+        .fetch_all(&mut *tx)
+        .await?
+        .iter()
+        .map(|row| {
+            let n = row_to_nachricht(row)?;
+            Ok(KontoNachricht {
+                id: n.id,
+                channel_id: n.channel_id,
+                kanal_name: row.try_get("kanal_name")?,
+                content: n.content,
+                message_type: n.message_type,
+                reply_to: n.reply_to,
+                created_at: n.created_at,
+                edited_at: n.edited_at,
+                deleted_at: n.deleted_at,
+            })
+        })
+        .collect::<DbResult<Vec<_>>>()?;
+
+        let dateien = sqlx::query(
+            "SELECT f.id, f.channel_id, f.uploader_id, f.filename, f.mime_type, f.size_bytes,
+                    f.storage_path, f.checksum, f.created_at, f.deleted_at, c.name AS kanal_name
+             FROM files f
+             JOIN channels c ON c.id = f.channel_id
+             WHERE f.uploader_id = $1
+             ORDER BY f.created_at, f.seq",
+        )
+        .bind(user_id)
+        .fetch_all(&mut *tx)
+        .await?
+        .iter()
+        .map(|row| {
+            let d = row_to_datei(row)?;
+            Ok(KontoDatei {
+                id: d.id,
+                channel_id: d.channel_id,
+                kanal_name: row.try_get("kanal_name")?,
+                filename: d.filename,
+                mime_type: d.mime_type,
+                size_bytes: d.size_bytes,
+                checksum: d.checksum,
+                created_at: d.created_at,
+                deleted_at: d.deleted_at,
+            })
+        })
+        .collect::<DbResult<Vec<_>>>()?;
+
+        let audit = sqlx::query(
+            "SELECT id, actor_id, action, target_type, target_id, details_json, timestamp
+             FROM audit_log WHERE actor_id = $1
+             ORDER BY timestamp, seq",
+        )
+        .bind(user_id)
+        .fetch_all(&mut *tx)
+        .await?
+        .iter()
+        .map(row_to_audit)
+        .collect::<DbResult<Vec<_>>>()?;
+
+        tx.commit().await?;
+
+        Ok(Some(KontoDaten {
+            profil: KontoProfil {
+                id: benutzer.id,
+                username: benutzer.username,
+                created_at: benutzer.created_at,
+                last_login: benutzer.last_login,
+                is_active: benutzer.is_active,
+                server_gruppen,
+            },
+            nachrichten,
+            dateien,
+            audit,
+        }))
+    }
+
+    async fn export_anlegen(
+        &self,
+        data: NeuerKontoExport<'_>,
+    ) -> DbResult<Option<KontoExportRecord>> {
+        benutzer_pruefen(self, data.user_id).await?;
+
+        let id = Uuid::new_v4();
+
+        // Pruefen und Anlegen in einer Anweisung; die Sperre auf der
+        // Benutzerzeile serialisiert gleichzeitige Anforderungen
+        let mut tx = self.pool.begin().await?;
+        sqlx::query("SELECT 1 FROM users WHERE id = $1 FOR UPDATE")
+            .bind(data.user_id)
+            .execute(&mut *tx)
+            .await?;
+        let angelegt = sqlx::query(
+            "INSERT INTO account_exports
+             (id, user_id, token_hash, storage_path, created_at, expires_at)
+             SELECT $1, $2, $3, $4, $5, $6
+             WHERE $7::timestamptz IS NULL OR NOT EXISTS (
+                 SELECT 1 FROM account_exports WHERE user_id = $2 AND created_at >= $7
+             )",
+        )
+        .bind(id)
+        .bind(data.user_id)
+        .bind(data.token_hash)
+        .bind(data.storage_path)
+        .bind(jetzt())
+        .bind(data.expires_at)
+        .bind(data.gesperrt_seit)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+        tx.commit().await?;
+
+        if angelegt == 0 {
+            return Ok(None);
+        }
+        export_laden(self, id).await
+    }
+
+    async fn export_abschliessen(&self, id: Uuid, size_bytes: i64) -> DbResult<()> {
+        let affected = sqlx::query(
+            "UPDATE account_exports SET size_bytes = $1, completed_at = $2 WHERE id = $3",
+        )
+        .bind(size_bytes)
+        .bind(jetzt())
+        .bind(id)
+        .execute(&self.pool)
+        .await?
+        .rows_affected();
+        if affected == 0 {
+            return Err(DbError::nicht_gefunden(format!("Konto-Export {id}")));
+        }
+        Ok(())
+    }
+
+    async fn export_entfernen(&self, id: Uuid) -> DbResult<()> {
+        sqlx::query("DELETE FROM account_exports WHERE id = $1")
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    async fn export_einloesen(
+        &self,
+        token_hash: &str,
+        jetzt: DateTime<Utc>,
+    ) -> DbResult<Option<KontoExportRecord>> {
+        let row = sqlx::query(&format!(
+            "UPDATE account_exports SET downloaded_at = $1
+             WHERE token_hash = $2 AND completed_at IS NOT NULL
+               AND downloaded_at IS NULL AND expires_at > $1
+             RETURNING {EXPORT_SPALTEN}"
+        ))
+        .bind(jetzt)
+        .bind(token_hash)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        row.map(|r| row_to_export(&r)).transpose()
+    }
+
+    async fn exporte_ablaufen(&self, jetzt: DateTime<Utc>) -> DbResult<Vec<KontoExportRecord>> {
+        sqlx::query(&format!(
+            "DELETE FROM account_exports WHERE expires_at <= $1 RETURNING {EXPORT_SPALTEN}"
+        ))
+        .bind(jetzt)
+        .fetch_all(&self.pool)
+        .await?
+        .iter()
+        .map(row_to_export)
+        .collect()
+    }
+
+    async fn konto_loeschen(&self, auftrag: KontoLoeschAuftrag<'_>) -> DbResult<KontoLoeschung> {
+        if auftrag.user_id == GELOESCHTER_BENUTZER {
+            return Err(DbError::UngueltigeDaten(
+                "Der Platzhalter fuer geloeschte Konten kann nicht geloescht werden".into(),
+            ));
+        }
+        let user_id = auftrag.user_id;
+        benutzer_pruefen(self, user_id).await?;
+        let mut ergebnis = KontoLoeschung::default();
+
+        // Phase 1: Nachrichten auf den Platzhalter umschreiben
+        let mut tx = self.pool.begin().await?;
+        let abfrage = match auftrag.richtlinie {
+            NachrichtenRichtlinie::Behalten => {
+                sqlx::query("UPDATE chat_messages SET sender_id = $1 WHERE sender_id = $2")
+                    .bind(GELOESCHTER_BENUTZER)
+                    .bind(user_id)
+            }
+            NachrichtenRichtlinie::Leeren => sqlx::query(
+                "UPDATE chat_messages
+                 SET sender_id = $1, content = '', deleted_at = COALESCE(deleted_at, $2)
+                 WHERE sender_id = $3",
+            )
+            .bind(GELOESCHTER_BENUTZER)
+            .bind(jetzt())
+            .bind(user_id),
+        };
+        ergebnis.nachrichten = abfrage.execute(&mut *tx).await?.rows_affected();
+        tx.commit().await?;
+
+        // Phase 2: Dateien loeschen und Kontingent freigeben
+        let mut tx = self.pool.begin().await?;
+        ergebnis.dateien = sqlx::query(
+            "DELETE FROM files WHERE uploader_id = $1
+             RETURNING id, channel_id, uploader_id, filename, mime_type, size_bytes,
+                       storage_path, checksum, created_at, deleted_at",
+        )
+        .bind(user_id)
+        .fetch_all(&mut *tx)
+        .await?
+        .iter()
+        .map(row_to_datei)
+        .collect::<DbResult<Vec<_>>>()?;
+        // Weich geloeschte Dateien wurden bereits vom Kontingent abgezogen
+        let belegt: i64 = ergebnis
+            .dateien
+            .iter()
+            .filter(|d| d.deleted_at.is_none())
+            .map(|d| d.size_bytes)
+            .sum();
+        sqlx::query(
+            "UPDATE file_quotas SET current_usage = GREATEST(0, current_usage - $1)
+             WHERE group_id = $2",
+        )
+        .bind(belegt)
+        .bind(auftrag.kontingent_gruppe)
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        // Phase 3: Berechtigungen und Gruppenmitgliedschaften
+        let mut tx = self.pool.begin().await?;
+        for sql in [
+            "DELETE FROM permissions WHERE target_type = 'user' AND target_id = $1",
+            "DELETE FROM user_server_groups WHERE user_id = $1",
+            "DELETE FROM user_channel_groups WHERE user_id = $1",
+        ] {
+            ergebnis.berechtigungen += sqlx::query(sql)
+                .bind(user_id)
+                .execute(&mut *tx)
+                .await?
+                .rows_affected();
+        }
+        tx.commit().await?;
+
+        // Phase 4: Benutzer entfernen (Exporte, Tokens, Einladungen und
+        // geplante Aktionen folgen per Fremdschluessel) und protokollieren
+        let mut tx = self.pool.begin().await?;
+        ergebnis.exporte =
+            sqlx::query_scalar("SELECT storage_path FROM account_exports WHERE user_id = $1")
+                .bind(user_id)
+                .fetch_all(&mut *tx)
+                .await?;
+        sqlx::query("DELETE FROM users WHERE id = $1")
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+        // Ohne Benutzer-ID und Namen: nur Ausloeser und Umfang
+        let details = serde_json::json!({
+            "ausloeser": if auftrag.admin_id.is_some() { "admin" } else { "selbst" },
+            "richtlinie": auftrag.richtlinie.als_str(),
+            "nachrichten": ergebnis.nachrichten,
+            "dateien": ergebnis.dateien.len(),
+            "berechtigungen": ergebnis.berechtigungen,
+        });
+        sqlx::query(
+            "INSERT INTO audit_log
+               (id, actor_id, action, target_type, target_id, details_json, timestamp)
+             VALUES ($1, $2, 'konto.geloescht', 'user', NULL, $3, $4)",
+        )
+        .bind(Uuid::new_v4())
+        .bind(auftrag.admin_id)
+        .bind(&details)
+        .bind(jetzt())
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        Ok(ergebnis)
+    }
+
+    async fn bereinigung_anlegen(
+        &self,
+        data: NeueInhaltsBereinigung,
+    ) -> DbResult<InhaltsBereinigungRecord> {
+        let row = sqlx::query(&format!(
+            "INSERT INTO content_cleanups
+             (id, user_id, since, channel_id, requested_by, created_at)
+             VALUES ($1, $2, $3, $4, $5, $6)
+             RETURNING {BEREINIGUNG_SPALTEN}"
+        ))
+        .bind(Uuid::new_v4())
+        .bind(data.user_id)
+        .bind(data.seit)
+        .bind(data.channel_id)
+        .bind(data.angefordert_von)
+        .bind(jetzt())
+        .fetch_one(&self.pool)
+        .await?;
+        row_to_bereinigung(&row)
+    }
+
+    async fn bereinigung_laden(&self, id: Uuid) -> DbResult<Option<InhaltsBereinigungRecord>> {
+        let row = sqlx::query(&format!(
+            "SELECT {BEREINIGUNG_SPALTEN} FROM content_cleanups WHERE id = $1"
+        ))
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?;
+        row.map(|r| row_to_bereinigung(&r)).transpose()
+    }
+
+    async fn bereinigung_stapel(
+        &self,
+        id: Uuid,
+        limit: i64,
+        kontingent_gruppe: &str,
+    ) -> DbResult<InhaltsStapel> {
+        let mut tx = self.pool.begin().await?;
+        // Die Sperre auf dem Auftrag serialisiert gleichzeitige Laeufe
+        let auftrag = sqlx::query(&format!(
+            "SELECT {BEREINIGUNG_SPALTEN} FROM content_cleanups WHERE id = $1 FOR UPDATE"
+        ))
+        .bind(id)
+        .fetch_optional(&mut *tx)
+        .await?
+        .map(|r| row_to_bereinigung(&r))
+        .transpose()?
+        .ok_or_else(|| DbError::nicht_gefunden(format!("Inhaltsbereinigung {id}")))?;
+        let zeitpunkt = jetzt();
+
+        // Nur noch nicht geloeschte Zeilen: ein wiederholter Stapel nach
+        // einem Abbruch findet genau den Rest
+        let nachrichten = sqlx::query(
+            "UPDATE chat_messages SET deleted_at = $1
+             WHERE id IN (
+                 SELECT id FROM chat_messages
+                 WHERE sender_id = $2 AND created_at >= $3 AND deleted_at IS NULL
+                   AND ($4::uuid IS NULL OR channel_id = $4)
+                 ORDER BY created_at
+                 LIMIT $5
+             )
+             RETURNING id, channel_id, sender_id, content, message_type,
+                       reply_to, created_at, edited_at, deleted_at, edit_count",
+        )
+        .bind(zeitpunkt)
+        .bind(auftrag.user_id)
+        .bind(auftrag.seit)
+        .bind(auftrag.channel_id)
+        .bind(limit)
+        .fetch_all(&mut *tx)
+        .await?
+        .iter()
+        .map(row_to_nachricht)
+        .collect::<DbResult<Vec<_>>>()?;
+
+        let dateien = sqlx::query(
+            "UPDATE files SET deleted_at = $1
+             WHERE id IN (
+                 SELECT id FROM files
+                 WHERE uploader_id = $2 AND created_at >= $3 AND deleted_at IS NULL
+                   AND ($4::uuid IS NULL OR channel_id = $4)
+                 ORDER BY created_at
+                 LIMIT $5
+             )
+             RETURNING id, channel_id, uploader_id, filename, mime_type, size_bytes,
+                       storage_path, checksum, created_at, deleted_at",
+        )
+        .bind(zeitpunkt)
+        .bind(auftrag.user_id)
+        .bind(auftrag.seit)
+        .bind(auftrag.channel_id)
+        .bind(limit)
+        .fetch_all(&mut *tx)
+        .await?
+        .iter()
+        .map(row_to_datei)
+        .collect::<DbResult<Vec<_>>>()?;
+        let belegt: i64 = dateien.iter().map(|d| d.size_bytes).sum();
+        sqlx::query(
+            "UPDATE file_quotas SET current_usage = GREATEST(0, current_usage - $1)
+             WHERE group_id = $2",
+        )
+        .bind(belegt)
+        .bind(kontingent_gruppe)
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query(
+            "UPDATE content_cleanups SET messages = messages + $1, files = files + $2
+             WHERE id = $3",
+        )
+        .bind(nachrichten.len() as i64)
+        .bind(dateien.len() as i64)
+        .bind(id)
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        Ok(InhaltsStapel {
+            nachrichten,
+            dateien,
+        })
+    }
+
+    async fn bereinigung_abschliessen(&self, id: Uuid) -> DbResult<InhaltsBereinigungRecord> {
+        let row = sqlx::query(&format!(
+            "UPDATE content_cleanups SET completed_at = COALESCE(completed_at, $1)
+             WHERE id = $2
+             RETURNING {BEREINIGUNG_SPALTEN}"
+        ))
+        .bind(jetzt())
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?;
+        row.map(|r| row_to_bereinigung(&r))
+            .transpose()?
+            .ok_or_else(|| DbError::nicht_gefunden(format!("Inhaltsbereinigung {id}")))
+    }
+}
+
+/// Gibt `NichtGefunden` zurueck, wenn der Benutzer nicht existiert
+async fn benutzer_pruefen(db: &PostgresDb, user_id: Uuid) -> DbResult<()> {
+    let vorhanden: Option<i32> = sqlx::query_scalar("SELECT 1 FROM users WHERE id = $1")
+        .bind(user_id)
+        .fetch_optional(&db.pool)
+        .await?;
+    vorhanden
+        .map(|_| ())
+        .ok_or_else(|| DbError::nicht_gefunden(format!("User {user_id}")))
+}
+
+async fn export_laden(db: &PostgresDb, id: Uuid) -> DbResult<Option<KontoExportRecord>> {
+    let row = sqlx::query(&format!(
+        "SELECT {EXPORT_SPALTEN} FROM account_exports WHERE id = $1"
+    ))
+    .bind(id)
+    .fetch_optional(&db.pool)
+    .await?;
+    row.map(|r| row_to_export(&r)).transpose()
+}
+
+fn row_to_export(row: &sqlx::postgres::PgRow) -> DbResult<KontoExportRecord> {
+    Ok(KontoExportRecord {
+        id: row.try_get("id")?,
+        user_id: row.try_get("user_id")?,
+        storage_path: row.try_get("storage_path")?,
+        size_bytes: row.try_get("size_bytes")?,
+        created_at: row.try_get("created_at")?,
+        completed_at: row.try_get("completed_at")?,
+        expires_at: row.try_get("expires_at")?,
+        downloaded_at: row.try_get("downloaded_at")?,
+    })
+}
+
+fn row_to_bereinigung(row: &sqlx::postgres::PgRow) -> DbResult<InhaltsBereinigungRecord> {
+    Ok(InhaltsBereinigungRecord {
+        id: row.try_get("id")?,
+        user_id: row.try_get("user_id")?,
+        seit: row.try_get("since")?,
+        channel_id: row.try_get("channel_id")?,
+        angefordert_von: row.try_get("requested_by")?,
+        nachrichten: row.try_get("messages")?,
+        dateien: row.try_get("files")?,
+        created_at: row.try_get("created_at")?,
+        completed_at: row.try_get("completed_at")?,
+    })
+}
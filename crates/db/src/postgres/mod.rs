@@ -0,0 +1,21 @@
+//! PostgreSQL-Backend-Implementierungen fuer alle Repository-Traits
+
+pub mod audit;
+pub mod bans;
+pub mod channel_templates;
+pub mod channels;
+pub mod chat;
+pub mod files;
+pub mod groups;
+pub mod import;
+pub mod invites;
+pub mod konto;
+pub mod permissions_repo;
+pub mod pool;
+pub mod server_starts;
+pub mod settings;
+pub mod soundboard;
+pub mod users;
+pub mod zeitplan;
+
+pub use pool::PostgresDb;
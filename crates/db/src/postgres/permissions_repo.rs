@@ -0,0 +1,234 @@
+//! PostgreSQL-Implementierung des PermissionRepository
+
+use sqlx::types::Json;
+use sqlx::Row;
+use uuid::Uuid;
+
+use crate::error::DbError;
+use crate::models::{BerechtigungsWert, BerechtigungsZiel, EffektiveBerechtigung, TriState};
+use crate::permissions::{
+    berechtigungen_aufloesen, berechtigungen_nachverfolgen, BerechtigungsEingabe, BerechtigungsSpur,
+};
+use crate::postgres::pool::PostgresDb;
+use crate::repository::{
+    ChannelGroupRepository, DbResult, PermissionRepository, ServerGroupRepository,
+};
+
+impl PermissionRepository for PostgresDb {
+    async fn get_permissions(
+        &self,
+        ziel: &BerechtigungsZiel,
+        channel_id: Option<Uuid>,
+    ) -> DbResult<Vec<(String, BerechtigungsWert)>> {
+        let rows = sqlx::query(
+            "SELECT permission_key, value_type, tri_state, int_limit, scope_json
+             FROM permissions
+             WHERE target_type = $1
+               AND target_id IS NOT DISTINCT FROM $2
+               AND channel_id IS NOT DISTINCT FROM $3",
+        )
+        .bind(ziel.typ_str())
+        .bind(ziel.id())
+        .bind(channel_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.iter().map(row_to_permission).collect()
+    }
+
+    async fn set_permission(
+        &self,
+        ziel: &BerechtigungsZiel,
+        permission_key: &str,
+        wert: BerechtigungsWert,
+        channel_id: Option<Uuid>,
+    ) -> DbResult<()> {
+        let (value_type, tri_state, int_limit, scope) = wert_zu_spalten(&wert);
+
+        // Upsert via DELETE + INSERT (ein Unique-Index greift nicht bei NULL-Spalten)
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query(
+            "DELETE FROM permissions
+             WHERE target_type = $1
+               AND target_id IS NOT DISTINCT FROM $2
+               AND permission_key = $3
+               AND channel_id IS NOT DISTINCT FROM $4",
+        )
+        .bind(ziel.typ_str())
+        .bind(ziel.id())
+        .bind(permission_key)
+        .bind(channel_id)
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query(
+            "INSERT INTO permissions
+               (id, target_type, target_id, permission_key, value_type, tri_state, int_limit, scope_json, channel_id)
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)",
+        )
+        .bind(Uuid::new_v4())
+        .bind(ziel.typ_str())
+        .bind(ziel.id())
+        .bind(permission_key)
+        .bind(value_type)
+        .bind(tri_state)
+        .bind(int_limit)
+        .bind(scope.map(Json))
+        .bind(channel_id)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn remove_permission(
+        &self,
+        ziel: &BerechtigungsZiel,
+        permission_key: &str,
+        channel_id: Option<Uuid>,
+    ) -> DbResult<bool> {
+        let affected = sqlx::query(
+            "DELETE FROM permissions
+             WHERE target_type = $1
+               AND target_id IS NOT DISTINCT FROM $2
+               AND permission_key = $3
+               AND channel_id IS NOT DISTINCT FROM $4",
+        )
+        .bind(ziel.typ_str())
+        .bind(ziel.id())
+        .bind(permission_key)
+        .bind(channel_id)
+        .execute(&self.pool)
+        .await?
+        .rows_affected();
+
+        Ok(affected > 0)
+    }
+
+    async fn resolve_effective_permissions(
+        &self,
+        user_id: Uuid,
+        channel_id: Uuid,
+    ) -> DbResult<Vec<EffektiveBerechtigung>> {
+        let eingabe = self
+            .berechtigungs_eingabe_laden(user_id, channel_id)
+            .await?;
+        let aufgeloest = berechtigungen_aufloesen(&eingabe);
+
+        Ok(aufgeloest
+            .into_values()
+            .map(|a| EffektiveBerechtigung {
+                permission_key: a.permission_key,
+                wert: a.wert,
+                quelle: a.stufe.to_string(),
+            })
+            .collect())
+    }
+
+    async fn resolve_with_trace(
+        &self,
+        user_id: Uuid,
+        channel_id: Uuid,
+    ) -> DbResult<Vec<BerechtigungsSpur>> {
+        let eingabe = self
+            .berechtigungs_eingabe_laden(user_id, channel_id)
+            .await?;
+        Ok(berechtigungen_nachverfolgen(&eingabe))
+    }
+}
+
+impl PostgresDb {
+    /// Laedt die Regeln aller Stufen fuer einen User in einem Kanal
+    async fn berechtigungs_eingabe_laden(
+        &self,
+        user_id: Uuid,
+        channel_id: Uuid,
+    ) -> DbResult<BerechtigungsEingabe> {
+        // 1. Individuelle Berechtigungen des Users
+        let individual = self
+            .get_permissions(&BerechtigungsZiel::Benutzer(user_id), Some(channel_id))
+            .await?;
+
+        // 2. Kanal-Gruppe des Users in diesem Kanal
+        let kanal_gruppe =
+            match ChannelGroupRepository::get_for_user_in_channel(self, user_id, channel_id).await?
+            {
+                Some(gruppe) => Some(
+                    self.get_permissions(
+                        &BerechtigungsZiel::KanalGruppe(gruppe.id),
+                        Some(channel_id),
+                    )
+                    .await?,
+                ),
+                None => None,
+            };
+
+        // 3. Kanal-Default
+        let kanal_default = self
+            .get_permissions(
+                &BerechtigungsZiel::KanalDefault(channel_id),
+                Some(channel_id),
+            )
+            .await?;
+
+        // 4. Server-Gruppen des Users (nach Prioritaet absteigend)
+        let mut server_gruppen = Vec::new();
+        for gruppe in ServerGroupRepository::list_for_user(self, user_id).await? {
+            let perms = self
+                .get_permissions(&BerechtigungsZiel::ServerGruppe(gruppe.id), None)
+                .await?;
+            server_gruppen.push((gruppe.name, perms));
+        }
+
+        // 5. Server-Default
+        let server_default = self
+            .get_permissions(&BerechtigungsZiel::ServerDefault, None)
+            .await?;
+
+        Ok(BerechtigungsEingabe {
+            individual,
+            kanal_gruppe,
+            kanal_default,
+            server_gruppen,
+            server_default,
+        })
+    }
+}
+
+fn row_to_permission(row: &sqlx::postgres::PgRow) -> DbResult<(String, BerechtigungsWert)> {
+    let key: String = row.try_get("permission_key")?;
+    let value_type: String = row.try_get("value_type")?;
+
+    let wert = match value_type.as_str() {
+        "tri_state" => {
+            let ts: Option<bool> = row.try_get("tri_state")?;
+            BerechtigungsWert::TriState(TriState::from_opt_bool(ts))
+        }
+        "int_limit" => BerechtigungsWert::IntLimit(row.try_get("int_limit")?),
+        "scope" => {
+            let Json(scope): Json<Vec<String>> = row.try_get("scope_json")?;
+            BerechtigungsWert::Scope(scope)
+        }
+        other => return Err(DbError::intern(format!("Unbekannter value_type: {other}"))),
+    };
+
+    Ok((key, wert))
+}
+
+pub(crate) type WertSpalten<'a> = (
+    &'static str,
+    Option<bool>,
+    Option<i64>,
+    Option<&'a [String]>,
+);
+
+/// Verteilt einen Wert auf die Spalten value_type, tri_state, int_limit, scope_json
+pub(crate) fn wert_zu_spalten(wert: &BerechtigungsWert) -> WertSpalten<'_> {
+    match wert {
+        BerechtigungsWert::TriState(ts) => ("tri_state", ts.to_opt_bool(), None, None),
+        BerechtigungsWert::IntLimit(limit) => ("int_limit", None, Some(*limit), None),
+        BerechtigungsWert::Scope(scope) => ("scope", None, None, Some(scope)),
+    }
+}
@@ -0,0 +1,104 @@
+//! PostgreSQL Connection Pool
+
+use chrono::{DateTime, SubsecRound, Utc};
+use sqlx::migrate::Migrator;
+use sqlx::postgres::{PgConnectOptions, PgPool, PgPoolOptions};
+use std::str::FromStr;
+use tracing::info;
+
+use crate::error::DbError;
+use crate::migrationen::{self, MigrationsFortschritt};
+use crate::repository::DatabaseConfig;
+
+/// Eingebettete Schema-Migrationen (eigener Satz, nicht die SQLite-Migrationen)
+static MIGRATIONEN: Migrator = sqlx::migrate!("./migrations_postgres");
+
+/// Wrapper um den PostgreSQL Connection Pool
+///
+/// Mehrere Server-Instanzen koennen sich eine Datenbank teilen; Migrationen
+/// sind ueber die Migrations-Sperre von sqlx gegen gleichzeitiges Anwenden
+/// geschuetzt.
+#[derive(Debug, Clone)]
+pub struct PostgresDb {
+    pub(crate) pool: PgPool,
+}
+
+impl PostgresDb {
+    /// Verbindet sich mit der Datenbank, fuehrt Migrationen aus
+    pub async fn oeffnen(config: &DatabaseConfig) -> Result<Self, DbError> {
+        Self::oeffnen_mit_fortschritt(config, |_| {}).await
+    }
+
+    /// Wie [`oeffnen`](Self::oeffnen), meldet aber jede ausstehende Migration
+    pub async fn oeffnen_mit_fortschritt(
+        config: &DatabaseConfig,
+        fortschritt: impl FnMut(MigrationsFortschritt),
+    ) -> Result<Self, DbError> {
+        let opts = PgConnectOptions::from_str(&config.url)?;
+        // Die URL kann ein Passwort enthalten und wird deshalb nicht geloggt
+        let host = opts.get_host().to_string();
+        let datenbank = opts.get_database().map(str::to_string);
+
+        let pool = PgPoolOptions::new()
+            .max_connections(config.max_verbindungen)
+            .connect_with(opts)
+            .await?;
+
+        info!(%host, ?datenbank, "PostgreSQL-Pool geoeffnet");
+
+        let db = Self { pool };
+        db.migrationen_mit_fortschritt(fortschritt).await?;
+
+        Ok(db)
+    }
+
+    /// Fuehrt alle ausstehenden Migrationen aus
+    pub async fn migrationen_ausfuehren(&self) -> Result<(), DbError> {
+        self.migrationen_mit_fortschritt(|_| {}).await
+    }
+
+    /// Fuehrt alle ausstehenden Migrationen aus und meldet jede vor dem Start
+    ///
+    /// Haelt dabei die Migrations-Sperre, damit parallel startende
+    /// Instanzen nicht dieselbe Migration anwenden.
+    pub async fn migrationen_mit_fortschritt(
+        &self,
+        fortschritt: impl FnMut(MigrationsFortschritt),
+    ) -> Result<(), DbError> {
+        use sqlx::migrate::Migrate as _;
+
+        let mut conn = self.pool.acquire().await?;
+        conn.lock().await?;
+        let ergebnis = migrationen::anwenden(&mut *conn, &MIGRATIONEN, fortschritt).await;
+        conn.unlock().await?;
+        ergebnis
+    }
+
+    /// Hoechste Schema-Version, die dieses Binary mitbringt
+    pub fn schema_version_binary() -> i64 {
+        migrationen::hoechste_version(&MIGRATIONEN)
+    }
+
+    /// Hoechste erfolgreich angewendete Schema-Version der Datenbank
+    pub async fn schema_version(&self) -> Result<i64, DbError> {
+        let mut conn = self.pool.acquire().await?;
+        migrationen::angewendete_version(&mut *conn).await
+    }
+
+    /// Prueft, ob die Datenbank Anfragen beantwortet (Health-Check)
+    pub async fn erreichbar(&self) -> bool {
+        sqlx::query("SELECT 1").execute(&self.pool).await.is_ok()
+    }
+
+    /// Gibt den internen Pool zurueck (fuer Tests)
+    pub fn pool(&self) -> &PgPool {
+        &self.pool
+    }
+}
+
+/// Aktuelle Zeit in der Genauigkeit von TIMESTAMPTZ (Mikrosekunden)
+///
+/// Rueckgabewerte stimmen so mit dem ueberein, was spaeter gelesen wird.
+pub(crate) fn jetzt() -> DateTime<Utc> {
+    Utc::now().trunc_subsecs(6)
+}
@@ -0,0 +1,57 @@
+//! PostgreSQL-Implementierung des ServerStartRepository
+
+use sqlx::Row;
+use uuid::Uuid;
+
+use crate::models::{NeuerServerStart, ServerStartRecord};
+use crate::postgres::pool::PostgresDb;
+use crate::repository::{DbResult, ServerStartRepository};
+
+impl ServerStartRepository for PostgresDb {
+    async fn create(&self, data: NeuerServerStart<'_>) -> DbResult<ServerStartRecord> {
+        let id = Uuid::new_v4();
+        sqlx::query(
+            "INSERT INTO server_starts (id, started_at, version, clean_shutdown, config_hash)
+             VALUES ($1, $2, $3, $4, $5)",
+        )
+        .bind(id)
+        .bind(data.started_at)
+        .bind(data.version)
+        .bind(data.clean_shutdown)
+        .bind(data.config_hash)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(ServerStartRecord {
+            id,
+            started_at: data.started_at,
+            version: data.version.to_string(),
+            clean_shutdown: data.clean_shutdown,
+            config_hash: data.config_hash.to_string(),
+        })
+    }
+
+    async fn list_recent(&self, limit: i64) -> DbResult<Vec<ServerStartRecord>> {
+        let rows = sqlx::query(
+            "SELECT id, started_at, version, clean_shutdown, config_hash
+             FROM server_starts
+             ORDER BY started_at DESC
+             LIMIT $1",
+        )
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.iter()
+            .map(|row| {
+                Ok(ServerStartRecord {
+                    id: row.try_get("id")?,
+                    started_at: row.try_get("started_at")?,
+                    version: row.try_get("version")?,
+                    clean_shutdown: row.try_get("clean_shutdown")?,
+                    config_hash: row.try_get("config_hash")?,
+                })
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,61 @@
+//! PostgreSQL-Implementierung des SettingsRepository
+
+use sqlx::Row;
+
+use crate::models::EinstellungRecord;
+use crate::postgres::pool::{jetzt, PostgresDb};
+use crate::repository::{DbResult, SettingsRepository};
+
+impl SettingsRepository for PostgresDb {
+    async fn get(&self, key: &str) -> DbResult<Option<String>> {
+        let wert = sqlx::query_scalar("SELECT value FROM server_settings WHERE key = $1")
+            .bind(key)
+            .fetch_optional(&self.pool)
+            .await?;
+        Ok(wert)
+    }
+
+    async fn set(&self, key: &str, value: &str) -> DbResult<()> {
+        sqlx::query(
+            "INSERT INTO server_settings (key, value, updated_at) VALUES ($1, $2, $3)
+             ON CONFLICT(key) DO UPDATE SET value = excluded.value,
+                                            updated_at = excluded.updated_at",
+        )
+        .bind(key)
+        .bind(value)
+        .bind(jetzt())
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    async fn set_default(&self, key: &str, value: &str) -> DbResult<bool> {
+        let affected = sqlx::query(
+            "INSERT INTO server_settings (key, value, updated_at) VALUES ($1, $2, $3)
+             ON CONFLICT DO NOTHING",
+        )
+        .bind(key)
+        .bind(value)
+        .bind(jetzt())
+        .execute(&self.pool)
+        .await?
+        .rows_affected();
+        Ok(affected > 0)
+    }
+
+    async fn list(&self) -> DbResult<Vec<EinstellungRecord>> {
+        let rows = sqlx::query("SELECT key, value, updated_at FROM server_settings ORDER BY key")
+            .fetch_all(&self.pool)
+            .await?;
+
+        rows.iter()
+            .map(|row| {
+                Ok(EinstellungRecord {
+                    key: row.try_get("key")?,
+                    value: row.try_get("value")?,
+                    updated_at: row.try_get("updated_at")?,
+                })
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,129 @@
+//! PostgreSQL-Implementierung des SoundboardRepository
+
+use sqlx::Row;
+use uuid::Uuid;
+
+use crate::error::DbError;
+use crate::models::{NeuerSound, SoundRecord};
+use crate::postgres::pool::{jetzt, PostgresDb};
+use crate::repository::{DbResult, SoundboardRepository};
+use crate::sqlite::soundboard::{frames_entpacken, frames_packen};
+
+const SPALTEN: &str =
+    "id, name, channel_id, file_id, frame_ms, duration_ms, created_by, created_at";
+
+impl SoundboardRepository for PostgresDb {
+    async fn create(&self, data: NeuerSound<'_>) -> DbResult<SoundRecord> {
+        let name = data.name.trim();
+        if name.is_empty() {
+            return Err(DbError::UngueltigeDaten(
+                "Soundname darf nicht leer sein".into(),
+            ));
+        }
+        if data.frames.is_empty() || data.frame_ms == 0 {
+            return Err(DbError::UngueltigeDaten("Sound ohne Audio-Frames".into()));
+        }
+        let frames = frames_packen(data.frames)?;
+        let duration_ms = u32::try_from(data.frames.len())
+            .ok()
+            .and_then(|n| n.checked_mul(data.frame_ms))
+            .ok_or_else(|| DbError::UngueltigeDaten("Sound ist zu lang".into()))?;
+
+        let id = Uuid::new_v4();
+        let now = jetzt();
+        sqlx::query(
+            "INSERT INTO soundboard_sounds
+             (id, name, channel_id, file_id, frame_ms, duration_ms, frames, created_by, created_at)
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)",
+        )
+        .bind(id)
+        .bind(name)
+        .bind(data.channel_id)
+        .bind(data.file_id)
+        .bind(i64::from(data.frame_ms))
+        .bind(i64::from(duration_ms))
+        .bind(frames)
+        .bind(data.created_by)
+        .bind(now)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| {
+            let msg = e.to_string();
+            if msg.contains("unique") {
+                DbError::Eindeutigkeit(format!("Sound '{name}' existiert bereits"))
+            } else {
+                DbError::Sqlx(e)
+            }
+        })?;
+
+        Ok(SoundRecord {
+            id,
+            name: name.to_string(),
+            channel_id: data.channel_id,
+            file_id: data.file_id,
+            frame_ms: data.frame_ms,
+            duration_ms,
+            created_by: data.created_by,
+            created_at: now,
+        })
+    }
+
+    async fn get_by_id(&self, id: Uuid) -> DbResult<Option<SoundRecord>> {
+        let row = sqlx::query(&format!(
+            "SELECT {SPALTEN} FROM soundboard_sounds WHERE id = $1"
+        ))
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        row.map(|r| row_to_sound(&r)).transpose()
+    }
+
+    async fn list(&self, channel_id: Option<Uuid>) -> DbResult<Vec<SoundRecord>> {
+        let rows = sqlx::query(&format!(
+            "SELECT {SPALTEN} FROM soundboard_sounds
+             WHERE $1::uuid IS NULL OR channel_id IS NULL OR channel_id = $1
+             ORDER BY name, id"
+        ))
+        .bind(channel_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.iter().map(row_to_sound).collect()
+    }
+
+    async fn get_frames(&self, id: Uuid) -> DbResult<Option<Vec<Vec<u8>>>> {
+        let blob: Option<Vec<u8>> =
+            sqlx::query_scalar("SELECT frames FROM soundboard_sounds WHERE id = $1")
+                .bind(id)
+                .fetch_optional(&self.pool)
+                .await?;
+
+        blob.map(|b| frames_entpacken(&b)).transpose()
+    }
+
+    async fn delete(&self, id: Uuid) -> DbResult<bool> {
+        let affected = sqlx::query("DELETE FROM soundboard_sounds WHERE id = $1")
+            .bind(id)
+            .execute(&self.pool)
+            .await?
+            .rows_affected();
+        Ok(affected > 0)
+    }
+}
+
+fn row_to_sound(row: &sqlx::postgres::PgRow) -> DbResult<SoundRecord> {
+    let frame_ms: i64 = row.try_get("frame_ms")?;
+    let duration_ms: i64 = row.try_get("duration_ms")?;
+
+    Ok(SoundRecord {
+        id: row.try_get("id")?,
+        name: row.try_get("name")?,
+        channel_id: row.try_get("channel_id")?,
+        file_id: row.try_get("file_id")?,
+        frame_ms: frame_ms as u32,
+        duration_ms: duration_ms as u32,
+        created_by: row.try_get("created_by")?,
+        created_at: row.try_get("created_at")?,
+    })
+}
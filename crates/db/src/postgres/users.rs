@@ -0,0 +1,175 @@
+//! PostgreSQL-Implementierung des UserRepository
+
+use sqlx::{Postgres, QueryBuilder, Row};
+use uuid::Uuid;
+
+use crate::error::DbError;
+use crate::models::{BenutzerRecord, BenutzerUpdate, NeuerBenutzer, GELOESCHTER_BENUTZER};
+use crate::postgres::pool::{jetzt, PostgresDb};
+use crate::repository::{DbResult, UserRepository};
+
+impl UserRepository for PostgresDb {
+    async fn create(&self, data: NeuerBenutzer<'_>) -> DbResult<BenutzerRecord> {
+        let id = Uuid::new_v4();
+        let now = jetzt();
+
+        sqlx::query(
+            "INSERT INTO users (id, username, password_hash, created_at, is_active, password_changed)
+             VALUES ($1, $2, $3, $4, TRUE, FALSE)",
+        )
+        .bind(id)
+        .bind(data.username)
+        .bind(data.password_hash)
+        .bind(now)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| {
+            let msg = e.to_string();
+            if msg.contains("unique") {
+                DbError::Eindeutigkeit(format!("Benutzername '{}' bereits vergeben", data.username))
+            } else {
+                DbError::Sqlx(e)
+            }
+        })?;
+
+        Ok(BenutzerRecord {
+            id,
+            username: data.username.to_string(),
+            password_hash: data.password_hash.to_string(),
+            created_at: now,
+            last_login: None,
+            is_active: true,
+            password_changed: false,
+        })
+    }
+
+    async fn get_by_id(&self, id: Uuid) -> DbResult<Option<BenutzerRecord>> {
+        let row = sqlx::query(
+            "SELECT id, username, password_hash, created_at, last_login, is_active, password_changed
+             FROM users WHERE id = $1",
+        )
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        row.map(|r| row_to_benutzer(&r)).transpose()
+    }
+
+    async fn get_by_name(&self, username: &str) -> DbResult<Option<BenutzerRecord>> {
+        let row = sqlx::query(
+            "SELECT id, username, password_hash, created_at, last_login, is_active, password_changed
+             FROM users WHERE username = $1",
+        )
+        .bind(username)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        row.map(|r| row_to_benutzer(&r)).transpose()
+    }
+
+    async fn update(&self, id: Uuid, data: BenutzerUpdate) -> DbResult<BenutzerRecord> {
+        if data.username.is_none()
+            && data.password_hash.is_none()
+            && data.is_active.is_none()
+            && data.last_login.is_none()
+            && data.password_changed.is_none()
+        {
+            return self
+                .get_by_id(id)
+                .await?
+                .ok_or_else(|| DbError::nicht_gefunden(format!("User {id}")));
+        }
+
+        // Dynamisches UPDATE – nur gesetzte Felder aendern
+        let mut q = QueryBuilder::<Postgres>::new("UPDATE users SET ");
+        let mut sets = q.separated(", ");
+        if let Some(v) = data.username {
+            sets.push("username = ").push_bind_unseparated(v);
+        }
+        if let Some(v) = data.password_hash {
+            sets.push("password_hash = ").push_bind_unseparated(v);
+        }
+        if let Some(v) = data.is_active {
+            sets.push("is_active = ").push_bind_unseparated(v);
+        }
+        if let Some(v) = data.last_login {
+            sets.push("last_login = ").push_bind_unseparated(v);
+        }
+        if let Some(v) = data.password_changed {
+            sets.push("password_changed = ").push_bind_unseparated(v);
+        }
+        q.push(" WHERE id = ").push_bind(id);
+
+        let affected = q.build().execute(&self.pool).await?.rows_affected();
+        if affected == 0 {
+            return Err(DbError::nicht_gefunden(format!("User {id}")));
+        }
+
+        self.get_by_id(id)
+            .await?
+            .ok_or_else(|| DbError::intern("User nach Update nicht gefunden"))
+    }
+
+    async fn delete(&self, id: Uuid) -> DbResult<bool> {
+        // Weicher Loeschvorgang: is_active = FALSE
+        let affected = sqlx::query("UPDATE users SET is_active = FALSE WHERE id = $1")
+            .bind(id)
+            .execute(&self.pool)
+            .await?
+            .rows_affected();
+        Ok(affected > 0)
+    }
+
+    async fn list(&self, nur_aktive: bool) -> DbResult<Vec<BenutzerRecord>> {
+        // Der Platzhalter fuer geloeschte Konten ist kein echter Benutzer
+        let rows = sqlx::query(
+            "SELECT id, username, password_hash, created_at, last_login, is_active, password_changed
+             FROM users WHERE (is_active OR NOT $1) AND id <> $2 ORDER BY username",
+        )
+        .bind(nur_aktive)
+        .bind(GELOESCHTER_BENUTZER)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.iter().map(row_to_benutzer).collect()
+    }
+
+    async fn authenticate(
+        &self,
+        username: &str,
+        password_hash: &str,
+    ) -> DbResult<Option<BenutzerRecord>> {
+        let row = sqlx::query(
+            "SELECT id, username, password_hash, created_at, last_login, is_active, password_changed
+             FROM users
+             WHERE username = $1 AND password_hash = $2 AND is_active",
+        )
+        .bind(username)
+        .bind(password_hash)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        row.map(|r| row_to_benutzer(&r)).transpose()
+    }
+
+    async fn update_last_login(&self, id: Uuid) -> DbResult<()> {
+        sqlx::query("UPDATE users SET last_login = $1 WHERE id = $2")
+            .bind(jetzt())
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+}
+
+pub(crate) fn row_to_benutzer(row: &sqlx::postgres::PgRow) -> DbResult<BenutzerRecord> {
+    Ok(BenutzerRecord {
+        id: row.try_get("id")?,
+        username: row.try_get("username")?,
+        password_hash: row.try_get("password_hash")?,
+        created_at: row.try_get("created_at")?,
+        last_login: row.try_get("last_login")?,
+        is_active: row.try_get("is_active")?,
+        password_changed: row.try_get("password_changed")?,
+    })
+}
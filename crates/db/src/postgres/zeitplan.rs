@@ -0,0 +1,157 @@
+//! PostgreSQL-Implementierung des ZeitplanRepository
+
+use chrono::{DateTime, SubsecRound, Utc};
+use sqlx::types::Json;
+use sqlx::Row;
+use uuid::Uuid;
+
+use crate::error::DbError;
+use crate::models::{GeplanteAktion, GeplanteAktionRecord, NeueGeplanteAktion};
+use crate::postgres::pool::PostgresDb;
+use crate::repository::{DbResult, ZeitplanRepository};
+use crate::zeitplan::Zeitplan;
+
+const SPALTEN: &str = "id, name, action_json, run_at, cron, time_zone, next_run_at, last_run_at,
+                       created_by, scopes_json, cancelled, created_at";
+
+/// Zeitpunkte auf ganze Sekunden, wie sie auch das SQLite-Backend speichert
+fn sekunden(zeit: DateTime<Utc>) -> DateTime<Utc> {
+    zeit.trunc_subsecs(0)
+}
+
+impl ZeitplanRepository for PostgresDb {
+    async fn create(&self, data: NeueGeplanteAktion<'_>) -> DbResult<GeplanteAktionRecord> {
+        if data.name.trim().is_empty() {
+            return Err(DbError::UngueltigeDaten(
+                "Name der geplanten Aktion darf nicht leer sein".into(),
+            ));
+        }
+
+        let id = Uuid::new_v4();
+        let (run_at, cron) = match &data.zeitplan {
+            Zeitplan::Einmalig(zeitpunkt) => (Some(sekunden(*zeitpunkt)), None),
+            Zeitplan::Wiederkehrend { cron, .. } => (None, Some(cron.als_str())),
+        };
+
+        sqlx::query(
+            "INSERT INTO scheduled_actions
+             (id, name, action_json, run_at, cron, time_zone, next_run_at, created_by,
+              scopes_json, created_at)
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)",
+        )
+        .bind(id)
+        .bind(data.name.trim())
+        .bind(Json(&data.aktion))
+        .bind(run_at)
+        .bind(cron)
+        .bind(data.zeitplan.zeitzone().name())
+        .bind(sekunden(data.next_run_at))
+        .bind(data.created_by)
+        .bind(data.scopes.map(Json))
+        .bind(sekunden(Utc::now()))
+        .execute(&self.pool)
+        .await?;
+
+        ZeitplanRepository::get_by_id(self, id)
+            .await?
+            .ok_or_else(|| DbError::intern("Geplante Aktion nach dem Anlegen nicht gefunden"))
+    }
+
+    async fn get_by_id(&self, id: Uuid) -> DbResult<Option<GeplanteAktionRecord>> {
+        let row = sqlx::query(&format!(
+            "SELECT {SPALTEN} FROM scheduled_actions WHERE id = $1"
+        ))
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        row.map(|r| row_to_aktion(&r)).transpose()
+    }
+
+    async fn list(&self) -> DbResult<Vec<GeplanteAktionRecord>> {
+        let rows = sqlx::query(&format!(
+            "SELECT {SPALTEN} FROM scheduled_actions ORDER BY created_at, id"
+        ))
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.iter().map(row_to_aktion).collect()
+    }
+
+    async fn cancel(&self, id: Uuid) -> DbResult<bool> {
+        let affected = sqlx::query(
+            "UPDATE scheduled_actions SET cancelled = TRUE, next_run_at = NULL
+             WHERE id = $1 AND NOT cancelled",
+        )
+        .bind(id)
+        .execute(&self.pool)
+        .await?
+        .rows_affected();
+        Ok(affected > 0)
+    }
+
+    async fn due(&self, jetzt: DateTime<Utc>) -> DbResult<Vec<GeplanteAktionRecord>> {
+        let rows = sqlx::query(&format!(
+            "SELECT {SPALTEN} FROM scheduled_actions
+             WHERE NOT cancelled AND next_run_at IS NOT NULL AND next_run_at <= $1
+             ORDER BY next_run_at, id"
+        ))
+        .bind(sekunden(jetzt))
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.iter().map(row_to_aktion).collect()
+    }
+
+    async fn claim(
+        &self,
+        id: Uuid,
+        geplant: DateTime<Utc>,
+        naechste: Option<DateTime<Utc>>,
+        jetzt: DateTime<Utc>,
+    ) -> DbResult<bool> {
+        // Bedingtes UPDATE: bei mehreren Instanzen gewinnt genau eine
+        let affected = sqlx::query(
+            "UPDATE scheduled_actions SET next_run_at = $1, last_run_at = $2
+             WHERE id = $3 AND NOT cancelled AND next_run_at = $4",
+        )
+        .bind(naechste.map(sekunden))
+        .bind(sekunden(jetzt))
+        .bind(id)
+        .bind(sekunden(geplant))
+        .execute(&self.pool)
+        .await?
+        .rows_affected();
+        Ok(affected > 0)
+    }
+}
+
+fn row_to_aktion(row: &sqlx::postgres::PgRow) -> DbResult<GeplanteAktionRecord> {
+    let id: Uuid = row.try_get("id")?;
+
+    let run_at: Option<DateTime<Utc>> = row.try_get("run_at")?;
+    let cron: Option<String> = row.try_get("cron")?;
+    let time_zone: String = row.try_get("time_zone")?;
+    let zeitplan = match (run_at, cron) {
+        (Some(run_at), _) => Zeitplan::Einmalig(run_at),
+        (None, Some(cron)) => Zeitplan::wiederkehrend(&cron, &time_zone)
+            .map_err(|e| DbError::intern(format!("Zeitplan {id}: {e}")))?,
+        (None, None) => return Err(DbError::intern(format!("Zeitplan {id} ohne Zeitpunkt"))),
+    };
+
+    let Json(aktion): Json<GeplanteAktion> = row.try_get("action_json")?;
+    let scopes: Option<Json<Vec<String>>> = row.try_get("scopes_json")?;
+
+    Ok(GeplanteAktionRecord {
+        id,
+        name: row.try_get("name")?,
+        aktion,
+        zeitplan,
+        next_run_at: row.try_get("next_run_at")?,
+        last_run_at: row.try_get("last_run_at")?,
+        created_by: row.try_get("created_by")?,
+        scopes: scopes.map(|Json(s)| s),
+        cancelled: row.try_get("cancelled")?,
+        created_at: row.try_get("created_at")?,
+    })
+}
@@ -3,16 +3,24 @@
 //! Das Repository-Pattern entkoppelt die Geschaeftslogik von der konkreten
 //! Datenbank-Implementierung. Alle Traits sind async und thread-safe.
 
+use chrono::{DateTime, Utc};
 use uuid::Uuid;
 
 use crate::error::DbError;
 use crate::models::{
     AuditLogFilter, AuditLogRecord, BanRecord, BenutzerRecord, BenutzerUpdate, BerechtigungsWert,
-    BerechtigungsZiel, ChatNachrichtRecord, DateiKontingentRecord, DateiRecord,
-    EffektiveBerechtigung, EinladungRecord, KanalGruppeRecord, KanalRecord, KanalUpdate,
-    NachrichtenFilter, NeueDatei, NeueEinladung, NeueKanalGruppe, NeueNachricht, NeueServerGruppe,
-    NeuerBan, NeuerBenutzer, NeuerKanal, ServerGruppeRecord,
+    BerechtigungsZiel, ChatNachrichtRecord, DateiKontingentRecord, DateiRecord, DateiZugriffFilter,
+    DateiZugriffRecord, EffektiveBerechtigung, EinladungRecord, EinstellungRecord,
+    GeplanteAktionRecord, ImportBenutzer, ImportServerGruppe, InhaltsBereinigungRecord,
+    InhaltsStapel, KanalFilter, KanalGruppeRecord, KanalRecord, KanalUpdate, KanalVorlageRecord,
+    KanalbaumGrenzen, KontoDaten, KontoExportRecord, KontoLoeschAuftrag, KontoLoeschung,
+    NachrichtenFilter, NachrichtenSuche, NeueDatei, NeueEinladung, NeueGeplanteAktion,
+    NeueInhaltsBereinigung, NeueKanalGruppe, NeueKanalVorlage, NeueNachricht, NeueServerGruppe,
+    NeuerAuditEintrag, NeuerBan, NeuerBenutzer, NeuerDateiZugriff, NeuerKanal, NeuerKontoExport,
+    NeuerServerStart, NeuerSound, NeuerUpload, ServerGruppeRecord, ServerStartRecord, SoundRecord,
+    UploadRecord, VorlagenKnoten,
 };
+use crate::permissions::BerechtigungsSpur;
 
 pub type DbResult<T> = Result<T, DbError>;
 
@@ -110,6 +118,12 @@ pub trait ChannelRepository: Send + Sync {
     /// Alle Kanaele auflisten (flach, sortiert nach sort_order)
     async fn list(&self) -> DbResult<Vec<KanalRecord>>;
 
+    /// Kanaele gefiltert und seitenweise auflisten (Reihenfolge wie `list`)
+    async fn list_filtered(&self, filter: KanalFilter) -> DbResult<Vec<KanalRecord>>;
+
+    /// Anzahl der Kanaele, auf die der Filter passt (ohne limit/offset)
+    async fn count_filtered(&self, filter: KanalFilter) -> DbResult<i64>;
+
     /// Kanal aktualisieren
     async fn update(&self, id: Uuid, data: KanalUpdate) -> DbResult<KanalRecord>;
 
@@ -163,6 +177,15 @@ pub trait PermissionRepository: Send + Sync {
         user_id: Uuid,
         channel_id: Uuid,
     ) -> DbResult<Vec<EffektiveBerechtigung>>;
+
+    /// Wie `resolve_effective_permissions`, aber mit Herkunft jeder Regel
+    ///
+    /// Nur fuer Diagnosezwecke; enthaelt alle Keys des Berechtigungskatalogs.
+    async fn resolve_with_trace(
+        &self,
+        user_id: Uuid,
+        channel_id: Uuid,
+    ) -> DbResult<Vec<BerechtigungsSpur>>;
 }
 
 // ---------------------------------------------------------------------------
@@ -282,6 +305,9 @@ pub trait AuditLogRepository: Send + Sync {
         details: serde_json::Value,
     ) -> DbResult<AuditLogRecord>;
 
+    /// Mehrere Ereignisse in einer Transaktion protokollieren (alle oder keins)
+    async fn log_events(&self, eintraege: &[NeuerAuditEintrag]) -> DbResult<()>;
+
     /// Ereignisse mit Filter auflisten
     async fn list_events(&self, filter: AuditLogFilter) -> DbResult<Vec<AuditLogRecord>>;
 
@@ -315,6 +341,12 @@ pub trait InviteRepository: Send + Sync {
     /// Gibt Err(EinladungErschoepft) zurueck wenn max_uses erreicht.
     async fn use_invite(&self, code: &str) -> DbResult<Option<EinladungRecord>>;
 
+    /// Eine Nutzung zurueckgeben (used_count verringern, nie unter 0)
+    ///
+    /// Fuer Einloesungen, die nach `use_invite` doch nicht zustande kommen.
+    /// Gibt false zurueck wenn die Einladung nicht (mehr) existiert.
+    async fn release_invite(&self, code: &str) -> DbResult<bool>;
+
     /// Einladung widerrufen
     async fn revoke(&self, id: Uuid) -> DbResult<bool>;
 }
@@ -341,13 +373,9 @@ pub trait ChatMessageRepository: Send + Sync {
     /// Nachricht weich loeschen (Soft-Delete)
     async fn soft_delete(&self, id: Uuid) -> DbResult<bool>;
 
-    /// Nachrichten eines Kanals nach Text durchsuchen
-    async fn search(
-        &self,
-        channel_id: Uuid,
-        query: &str,
-        limit: i64,
-    ) -> DbResult<Vec<ChatNachrichtRecord>>;
+    /// Nachrichten eines Kanals nach Text durchsuchen (neueste zuerst,
+    /// ohne geloeschte)
+    async fn search(&self, suche: NachrichtenSuche<'_>) -> DbResult<Vec<ChatNachrichtRecord>>;
 }
 
 // ---------------------------------------------------------------------------
@@ -377,4 +405,301 @@ pub trait FileRepository: Send + Sync {
 
     /// Aktuelle Speichernutzung einer Gruppe verringern
     async fn decrement_usage(&self, group_id: &str, bytes: i64) -> DbResult<()>;
+
+    /// Download im Zugriffsprotokoll vermerken (append-only)
+    async fn log_access(&self, data: NeuerDateiZugriff) -> DbResult<DateiZugriffRecord>;
+
+    /// Zugriffsprotokoll mit Filter und Paginierung auflisten (neueste zuerst)
+    async fn list_access(&self, filter: DateiZugriffFilter) -> DbResult<Vec<DateiZugriffRecord>>;
+
+    /// Anzahl der Zugriffe zaehlen (ignoriert limit/offset)
+    async fn count_access(&self, filter: DateiZugriffFilter) -> DbResult<i64>;
+
+    /// Zugriffe je Datei fuer mehrere Dateien in einer Abfrage zaehlen
+    ///
+    /// Dateien ohne Zugriff fehlen im Ergebnis.
+    async fn count_access_by_file(&self, file_ids: &[Uuid]) -> DbResult<Vec<(Uuid, i64)>>;
+
+    /// Protokolleintraege loeschen die aelter als `before` sind
+    async fn purge_access_before(&self, before: DateTime<Utc>) -> DbResult<u64>;
+
+    /// Datei-Upload anmelden (Datei folgt per HTTP)
+    async fn create_upload(&self, data: NeuerUpload<'_>) -> DbResult<UploadRecord>;
+
+    /// Angemeldeten Upload laden
+    async fn get_upload(&self, id: Uuid) -> DbResult<Option<UploadRecord>>;
+
+    /// Upload abschliessen: legt die Datei mit der Upload-ID an
+    ///
+    /// Gibt `None` zurueck, wenn der Upload nicht (mehr) angemeldet ist,
+    /// z.B. weil er bereits abgeschlossen wurde.
+    async fn complete_upload(
+        &self,
+        id: Uuid,
+        storage_path: &str,
+        checksum: &str,
+    ) -> DbResult<Option<DateiRecord>>;
+
+    /// Angemeldete Uploads entfernen, die vor `before` abgelaufen sind
+    async fn purge_uploads_before(&self, before: DateTime<Utc>) -> DbResult<u64>;
+}
+
+// ---------------------------------------------------------------------------
+// SoundboardRepository
+// ---------------------------------------------------------------------------
+
+/// Repository fuer Soundboard-Eintraege
+#[allow(async_fn_in_trait)]
+pub trait SoundboardRepository: Send + Sync {
+    /// Sound registrieren (Name je Kanal bzw. serverweit eindeutig)
+    async fn create(&self, data: NeuerSound<'_>) -> DbResult<SoundRecord>;
+
+    /// Sound anhand seiner ID laden
+    async fn get_by_id(&self, id: Uuid) -> DbResult<Option<SoundRecord>>;
+
+    /// Sounds auflisten, nach Name sortiert
+    ///
+    /// Ohne Kanal alle; mit Kanal die serverweiten und die des Kanals.
+    async fn list(&self, channel_id: Option<Uuid>) -> DbResult<Vec<SoundRecord>>;
+
+    /// Kodierte Frames eines Sounds laden
+    async fn get_frames(&self, id: Uuid) -> DbResult<Option<Vec<Vec<u8>>>>;
+
+    /// Sound entfernen; `false` wenn unbekannt
+    async fn delete(&self, id: Uuid) -> DbResult<bool>;
+}
+
+// ---------------------------------------------------------------------------
+// ChannelTemplateRepository
+// ---------------------------------------------------------------------------
+
+/// Repository fuer Kanal-Vorlagen
+#[allow(async_fn_in_trait)]
+pub trait ChannelTemplateRepository: Send + Sync {
+    /// Neue Vorlage anlegen (Definition wird vorher geprueft)
+    async fn create(&self, data: NeueKanalVorlage<'_>) -> DbResult<KanalVorlageRecord>;
+
+    /// Vorlage anhand ihrer ID laden
+    async fn get_by_id(&self, id: Uuid) -> DbResult<Option<KanalVorlageRecord>>;
+
+    /// Alle Vorlagen auflisten (mitgelieferte zuerst)
+    async fn list(&self) -> DbResult<Vec<KanalVorlageRecord>>;
+
+    /// Vorlage loeschen; mitgelieferte Vorlagen sind geschuetzt
+    async fn delete(&self, id: Uuid) -> DbResult<bool>;
+
+    /// Legt den Kanalbaum einer Vorlage unter `parent_id` an.
+    ///
+    /// Alles-oder-nichts: schlaegt ein Kanal fehl (Grenzen, unbekannte
+    /// Gruppe, ...), wird keiner der Kanaele angelegt. `name_prefix` wird
+    /// dem Namen des Wurzelkanals vorangestellt. Gibt die angelegten
+    /// Kanaele zurueck, der Wurzelkanal zuerst.
+    async fn instantiate(
+        &self,
+        vorlage_id: Uuid,
+        parent_id: Option<Uuid>,
+        name_prefix: Option<&str>,
+        grenzen: KanalbaumGrenzen,
+    ) -> DbResult<Vec<KanalRecord>>;
+}
+
+// ---------------------------------------------------------------------------
+// ImportRepository
+// ---------------------------------------------------------------------------
+
+/// Repository fuer Sammel-Importe (z.B. Migration von TeamSpeak 3)
+///
+/// Jede Methode schreibt einen Abschnitt in einer eigenen Transaktion:
+/// schlaegt ein Datensatz fehl, wird keiner des Abschnitts angelegt.
+#[allow(async_fn_in_trait)]
+pub trait ImportRepository: Send + Sync {
+    /// Legt mehrere Kanalbaeume auf Root-Ebene an
+    ///
+    /// Gibt die angelegten Kanaele in Pre-Order zurueck.
+    async fn kanaele_importieren(
+        &self,
+        wurzeln: &[VorlagenKnoten],
+        grenzen: KanalbaumGrenzen,
+    ) -> DbResult<Vec<KanalRecord>>;
+
+    /// Legt Server-Gruppen samt server-weiter Berechtigungen an
+    async fn server_gruppen_importieren(
+        &self,
+        gruppen: &[ImportServerGruppe],
+    ) -> DbResult<Vec<ServerGruppeRecord>>;
+
+    /// Legt Benutzer an und traegt sie in ihre Server-Gruppen ein
+    async fn benutzer_importieren(
+        &self,
+        benutzer: &[ImportBenutzer],
+    ) -> DbResult<Vec<BenutzerRecord>>;
+
+    /// Legt mehrere Einladungen an
+    async fn einladungen_importieren(
+        &self,
+        einladungen: &[NeueEinladung<'_>],
+    ) -> DbResult<Vec<EinladungRecord>>;
+}
+
+// ---------------------------------------------------------------------------
+// SettingsRepository
+// ---------------------------------------------------------------------------
+
+/// Repository fuer zur Laufzeit aenderbare Server-Einstellungen
+///
+/// Werte werden als Text abgelegt; die typisierten Zugriffe fallen auf den
+/// Standardwert zurueck, wenn ein Schluessel fehlt.
+#[allow(async_fn_in_trait)]
+pub trait SettingsRepository: Send + Sync {
+    /// Rohwert eines Schluessels laden
+    async fn get(&self, key: &str) -> DbResult<Option<String>>;
+
+    /// Wert setzen (legt den Schluessel bei Bedarf an)
+    async fn set(&self, key: &str, value: &str) -> DbResult<()>;
+
+    /// Wert nur setzen wenn der Schluessel noch fehlt; `true` wenn gesetzt
+    async fn set_default(&self, key: &str, value: &str) -> DbResult<bool>;
+
+    /// Alle Eintraege nach Schluessel sortiert
+    async fn list(&self) -> DbResult<Vec<EinstellungRecord>>;
+
+    /// Textwert oder `default`
+    async fn get_string(&self, key: &str, default: &str) -> DbResult<String> {
+        Ok(self.get(key).await?.unwrap_or_else(|| default.to_string()))
+    }
+
+    /// Ganzzahl oder `default`; ein nicht lesbarer Wert ist ein Fehler
+    async fn get_u32(&self, key: &str, default: u32) -> DbResult<u32> {
+        match self.get(key).await? {
+            None => Ok(default),
+            Some(wert) => wert.trim().parse().map_err(|_| {
+                DbError::UngueltigeDaten(format!("Einstellung '{key}' ist keine Zahl: '{wert}'"))
+            }),
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// ServerStartRepository
+// ---------------------------------------------------------------------------
+
+/// Repository fuer das Neustart-Protokoll (siehe [`crate::neustart`])
+#[allow(async_fn_in_trait)]
+pub trait ServerStartRepository: Send + Sync {
+    /// Serverstart festhalten
+    async fn create(&self, data: NeuerServerStart<'_>) -> DbResult<ServerStartRecord>;
+
+    /// Die letzten `limit` Starts, neueste zuerst
+    async fn list_recent(&self, limit: i64) -> DbResult<Vec<ServerStartRecord>>;
+}
+
+// ---------------------------------------------------------------------------
+// ZeitplanRepository
+// ---------------------------------------------------------------------------
+
+/// Repository fuer geplante Aktionen des Zeitplaners
+#[allow(async_fn_in_trait)]
+pub trait ZeitplanRepository: Send + Sync {
+    /// Geplante Aktion anlegen
+    async fn create(&self, data: NeueGeplanteAktion<'_>) -> DbResult<GeplanteAktionRecord>;
+
+    /// Geplante Aktion anhand ihrer ID laden
+    async fn get_by_id(&self, id: Uuid) -> DbResult<Option<GeplanteAktionRecord>>;
+
+    /// Alle geplanten Aktionen (inkl. erledigter und abgebrochener), aelteste zuerst
+    async fn list(&self) -> DbResult<Vec<GeplanteAktionRecord>>;
+
+    /// Bricht eine Aktion ab; `false` wenn unbekannt oder bereits abgebrochen
+    async fn cancel(&self, id: Uuid) -> DbResult<bool>;
+
+    /// Nicht abgebrochene Aktionen mit `next_run_at <= jetzt`, frueheste zuerst
+    async fn due(&self, jetzt: DateTime<Utc>) -> DbResult<Vec<GeplanteAktionRecord>>;
+
+    /// Beansprucht die Ausfuehrung, die fuer `geplant` ansteht
+    ///
+    /// Setzt `next_run_at` auf `naechste` und `last_run_at` auf `jetzt`, aber
+    /// nur solange `next_run_at` noch `geplant` ist. Gibt `true` zurueck, wenn
+    /// dieser Aufruf gewonnen hat – so laeuft jede Ausfuehrung hoechstens
+    /// einmal, auch nach einem Neustart mitten im Zeitfenster.
+    async fn claim(
+        &self,
+        id: Uuid,
+        geplant: DateTime<Utc>,
+        naechste: Option<DateTime<Utc>>,
+        jetzt: DateTime<Utc>,
+    ) -> DbResult<bool>;
+}
+
+// ---------------------------------------------------------------------------
+// KontoRepository
+// ---------------------------------------------------------------------------
+
+/// Repository fuer Datenexport und Loeschung ganzer Benutzerkonten
+#[allow(async_fn_in_trait)]
+pub trait KontoRepository: Send + Sync {
+    /// Sammelt alle gespeicherten Daten eines Benutzers (`None` wenn unbekannt)
+    async fn daten_sammeln(&self, user_id: Uuid) -> DbResult<Option<KontoDaten>>;
+
+    /// Legt einen Export an
+    ///
+    /// Gibt `None` zurueck, wenn seit `gesperrt_seit` bereits ein Export
+    /// angelegt wurde. Pruefung und Anlegen sind atomar.
+    async fn export_anlegen(
+        &self,
+        data: NeuerKontoExport<'_>,
+    ) -> DbResult<Option<KontoExportRecord>>;
+
+    /// Markiert das Archiv eines Exports als fertig geschrieben
+    async fn export_abschliessen(&self, id: Uuid, size_bytes: i64) -> DbResult<()>;
+
+    /// Entfernt einen Export (z.B. wenn das Archiv nicht erstellt werden konnte)
+    async fn export_entfernen(&self, id: Uuid) -> DbResult<()>;
+
+    /// Loest einen Download-Token ein
+    ///
+    /// Nur fertige, nicht abgelaufene und noch nicht abgeholte Exporte; jeder
+    /// Token gelingt hoechstens einmal, auch bei gleichzeitigen Aufrufen.
+    async fn export_einloesen(
+        &self,
+        token_hash: &str,
+        jetzt: DateTime<Utc>,
+    ) -> DbResult<Option<KontoExportRecord>>;
+
+    /// Entfernt alle Exporte mit `expires_at <= jetzt` und gibt sie zurueck
+    async fn exporte_ablaufen(&self, jetzt: DateTime<Utc>) -> DbResult<Vec<KontoExportRecord>>;
+
+    /// Loescht ein Konto
+    ///
+    /// Jede Phase laeuft in einer eigenen Transaktion: Nachrichten
+    /// anonymisieren, Dateien loeschen, Berechtigungen und Mitgliedschaften
+    /// entfernen, zuletzt den Benutzer samt Audit-Eintrag (ohne
+    /// personenbezogene Daten). Bricht eine Phase ab, kann die Loeschung
+    /// wiederholt werden.
+    async fn konto_loeschen(&self, auftrag: KontoLoeschAuftrag<'_>) -> DbResult<KontoLoeschung>;
+
+    /// Legt einen Auftrag zur Inhaltsbereinigung an
+    async fn bereinigung_anlegen(
+        &self,
+        data: NeueInhaltsBereinigung,
+    ) -> DbResult<InhaltsBereinigungRecord>;
+
+    /// Laedt einen Bereinigungsauftrag
+    async fn bereinigung_laden(&self, id: Uuid) -> DbResult<Option<InhaltsBereinigungRecord>>;
+
+    /// Loescht den naechsten Stapel eines Bereinigungsauftrags weich
+    ///
+    /// Hoechstens `limit` Nachrichten und `limit` Dateien, die noch nicht
+    /// geloescht sind. Die Dateien werden vom Kontingent abgezogen und der
+    /// Fortschritt des Auftrags in derselben Transaktion fortgeschrieben;
+    /// nach einem Abbruch setzt der naechste Aufruf dort fort. Ein leerer
+    /// Stapel heisst: nichts mehr zu tun.
+    async fn bereinigung_stapel(
+        &self,
+        id: Uuid,
+        limit: i64,
+        kontingent_gruppe: &str,
+    ) -> DbResult<InhaltsStapel>;
+
+    /// Markiert einen Bereinigungsauftrag als abgeschlossen
+    async fn bereinigung_abschliessen(&self, id: Uuid) -> DbResult<InhaltsBereinigungRecord>;
 }
@@ -5,7 +5,7 @@ use sqlx::Row;
 use uuid::Uuid;
 
 use crate::error::DbError;
-use crate::models::{AuditLogFilter, AuditLogRecord};
+use crate::models::{AuditLogFilter, AuditLogRecord, NeuerAuditEintrag};
 use crate::repository::{AuditLogRepository, DbResult};
 use crate::sqlite::bans::parse_opt_uuid;
 use crate::sqlite::pool::SqliteDb;
@@ -52,6 +52,28 @@ impl AuditLogRepository for SqliteDb {
         })
     }
 
+    async fn log_events(&self, eintraege: &[NeuerAuditEintrag]) -> DbResult<()> {
+        let mut tx = self.pool.begin().await?;
+        for eintrag in eintraege {
+            sqlx::query(
+                "INSERT INTO audit_log
+                   (id, actor_id, action, target_type, target_id, details_json, timestamp)
+                 VALUES (?, ?, ?, ?, ?, ?, ?)",
+            )
+            .bind(Uuid::new_v4().to_string())
+            .bind(eintrag.actor_id.map(|u| u.to_string()))
+            .bind(&eintrag.action)
+            .bind(&eintrag.target_type)
+            .bind(&eintrag.target_id)
+            .bind(serde_json::to_string(&eintrag.details)?)
+            .bind(eintrag.timestamp.to_rfc3339())
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+
     async fn list_events(&self, filter: AuditLogFilter) -> DbResult<Vec<AuditLogRecord>> {
         // Dynamische WHERE-Klausel aufbauen
         let mut conditions: Vec<&str> = Vec::new();
@@ -180,7 +202,7 @@ impl AuditLogRepository for SqliteDb {
     }
 }
 
-fn row_to_audit(row: &sqlx::sqlite::SqliteRow) -> DbResult<AuditLogRecord> {
+pub(crate) fn row_to_audit(row: &sqlx::sqlite::SqliteRow) -> DbResult<AuditLogRecord> {
     let id_str: String = row.try_get("id")?;
     let id = Uuid::parse_str(&id_str)
         .map_err(|e| DbError::intern(format!("Ungueltige AuditLog-UUID '{id_str}': {e}")))?;
@@ -0,0 +1,320 @@
+//! SQLite-Implementierung des ChannelTemplateRepository
+
+use chrono::Utc;
+use sqlx::{Row, Sqlite, Transaction};
+use uuid::Uuid;
+
+use crate::error::DbError;
+use crate::models::{
+    KanalRecord, KanalVorlageRecord, KanalbaumGrenzen, NeueKanalVorlage, VorlagenKnoten,
+    VorlagenZiel,
+};
+use crate::repository::{ChannelRepository, ChannelTemplateRepository, DbResult};
+use crate::sqlite::permissions_repo::wert_zu_spalten;
+use crate::sqlite::pool::SqliteDb;
+
+impl ChannelTemplateRepository for SqliteDb {
+    async fn create(&self, data: NeueKanalVorlage<'_>) -> DbResult<KanalVorlageRecord> {
+        if data.name.trim().is_empty() {
+            return Err(DbError::UngueltigeDaten(
+                "Vorlagenname darf nicht leer sein".into(),
+            ));
+        }
+        data.root.pruefen().map_err(DbError::UngueltigeDaten)?;
+
+        let id = Uuid::new_v4();
+        let now = Utc::now();
+        let definition = serde_json::to_string(&data.root)?;
+
+        sqlx::query(
+            "INSERT INTO channel_templates
+             (id, name, description, definition_json, builtin, created_by, created_at)
+             VALUES (?, ?, ?, ?, 0, ?, ?)",
+        )
+        .bind(id.to_string())
+        .bind(data.name)
+        .bind(data.description)
+        .bind(&definition)
+        .bind(data.created_by.map(|u| u.to_string()))
+        .bind(now.to_rfc3339())
+        .execute(&self.pool)
+        .await
+        .map_err(|e| {
+            let msg = e.to_string();
+            if msg.contains("UNIQUE") || msg.contains("unique") {
+                DbError::Eindeutigkeit(format!("Vorlage '{}' existiert bereits", data.name))
+            } else {
+                DbError::Sqlx(e)
+            }
+        })?;
+
+        Ok(KanalVorlageRecord {
+            id,
+            name: data.name.to_string(),
+            description: data.description.map(|s| s.to_string()),
+            root: data.root,
+            builtin: false,
+            created_by: data.created_by,
+            created_at: now,
+        })
+    }
+
+    async fn get_by_id(&self, id: Uuid) -> DbResult<Option<KanalVorlageRecord>> {
+        let row = sqlx::query(
+            "SELECT id, name, description, definition_json, builtin, created_by, created_at
+             FROM channel_templates WHERE id = ?",
+        )
+        .bind(id.to_string())
+        .fetch_optional(&self.pool)
+        .await?;
+
+        row.map(|r| row_to_vorlage(&r)).transpose()
+    }
+
+    async fn list(&self) -> DbResult<Vec<KanalVorlageRecord>> {
+        let rows = sqlx::query(
+            "SELECT id, name, description, definition_json, builtin, created_by, created_at
+             FROM channel_templates ORDER BY builtin DESC, name",
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.iter().map(row_to_vorlage).collect()
+    }
+
+    async fn delete(&self, id: Uuid) -> DbResult<bool> {
+        let builtin: Option<i64> =
+            sqlx::query_scalar("SELECT builtin FROM channel_templates WHERE id = ?")
+                .bind(id.to_string())
+                .fetch_optional(&self.pool)
+                .await?;
+
+        match builtin {
+            None => Ok(false),
+            Some(b) if b != 0 => Err(DbError::UngueltigeDaten(
+                "Mitgelieferte Vorlagen koennen nicht geloescht werden".into(),
+            )),
+            Some(_) => {
+                let affected = sqlx::query("DELETE FROM channel_templates WHERE id = ?")
+                    .bind(id.to_string())
+                    .execute(&self.pool)
+                    .await?
+                    .rows_affected();
+                Ok(affected > 0)
+            }
+        }
+    }
+
+    async fn instantiate(
+        &self,
+        vorlage_id: Uuid,
+        parent_id: Option<Uuid>,
+        name_prefix: Option<&str>,
+        grenzen: KanalbaumGrenzen,
+    ) -> DbResult<Vec<KanalRecord>> {
+        let vorlage = ChannelTemplateRepository::get_by_id(self, vorlage_id)
+            .await?
+            .ok_or_else(|| DbError::nicht_gefunden(format!("Kanal-Vorlage {vorlage_id}")))?;
+
+        let mut root = vorlage.root;
+        if let Some(prefix) = name_prefix.filter(|p| !p.is_empty()) {
+            root.name = format!("{prefix}{}", root.name);
+        }
+        root.pruefen().map_err(DbError::UngueltigeDaten)?;
+
+        // Alles in einer Transaktion: bei jedem Fehler wird `tx` ohne
+        // commit verworfen und SQLite rollt saemtliche Inserts zurueck.
+        let mut tx = self.pool.begin().await?;
+
+        let basis_tiefe = match parent_id {
+            Some(pid) => eltern_tiefe(&mut tx, pid).await?,
+            None => 0,
+        };
+        let neue_tiefe = basis_tiefe + root.tiefe();
+        if grenzen.max_tiefe > 0 && neue_tiefe > grenzen.max_tiefe {
+            return Err(DbError::UngueltigeDaten(format!(
+                "Verschachtelungstiefe {neue_tiefe} ueberschreitet das Limit von {}",
+                grenzen.max_tiefe
+            )));
+        }
+
+        let vorhanden: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM channels")
+            .fetch_one(&mut *tx)
+            .await?;
+        let gesamt = vorhanden as u64 + root.anzahl_kanaele() as u64;
+        if grenzen.max_kanaele > 0 && gesamt > grenzen.max_kanaele as u64 {
+            return Err(DbError::UngueltigeDaten(format!(
+                "Kanal-Limit ueberschritten: {gesamt} > {}",
+                grenzen.max_kanaele
+            )));
+        }
+
+        let ids = teilbaum_anlegen(&mut tx, &root, parent_id, 0).await?;
+
+        tx.commit().await?;
+
+        let mut kanaele = Vec::with_capacity(ids.len());
+        for id in ids {
+            let kanal = ChannelRepository::get_by_id(self, id)
+                .await?
+                .ok_or_else(|| DbError::intern("Kanal nach Instanziierung nicht gefunden"))?;
+            kanaele.push(kanal);
+        }
+        Ok(kanaele)
+    }
+}
+
+/// Legt einen Kanalbaum innerhalb der Transaktion an
+///
+/// Pre-Order: Eltern werden immer vor ihren Kindern angelegt. Gibt die IDs
+/// in dieser Reihenfolge zurueck, der Wurzelkanal zuerst.
+pub(crate) async fn teilbaum_anlegen(
+    tx: &mut Transaction<'_, Sqlite>,
+    root: &VorlagenKnoten,
+    parent_id: Option<Uuid>,
+    sort_order: i64,
+) -> DbResult<Vec<Uuid>> {
+    let mut ids = Vec::with_capacity(root.anzahl_kanaele());
+    let mut stapel: Vec<(&VorlagenKnoten, Option<Uuid>, i64)> = vec![(root, parent_id, sort_order)];
+    while let Some((knoten, eltern, sort_order)) = stapel.pop() {
+        let id = kanal_anlegen(tx, knoten, eltern, sort_order).await?;
+        ids.push(id);
+        for (i, kind) in knoten.children.iter().enumerate().rev() {
+            stapel.push((kind, Some(id), i as i64));
+        }
+    }
+    Ok(ids)
+}
+
+/// Tiefe eines bestehenden Kanals (Kanal auf Root-Ebene = 1)
+async fn eltern_tiefe(tx: &mut Transaction<'_, Sqlite>, kanal_id: Uuid) -> DbResult<u32> {
+    let mut tiefe = 0u32;
+    let mut aktuell = Some(kanal_id.to_string());
+    while let Some(id) = aktuell {
+        let eltern: Option<Option<String>> =
+            sqlx::query_scalar("SELECT parent_id FROM channels WHERE id = ?")
+                .bind(&id)
+                .fetch_optional(&mut **tx)
+                .await?;
+        let Some(eltern) = eltern else {
+            return Err(DbError::nicht_gefunden(format!("Kanal {id}")));
+        };
+        tiefe += 1;
+        // Schutz gegen zyklische parent_id-Ketten
+        if tiefe > 1024 {
+            return Err(DbError::intern("Zyklus in der Kanal-Hierarchie"));
+        }
+        aktuell = eltern;
+    }
+    Ok(tiefe)
+}
+
+/// Legt einen einzelnen Kanal samt Berechtigungen innerhalb der Transaktion an
+async fn kanal_anlegen(
+    tx: &mut Transaction<'_, Sqlite>,
+    knoten: &VorlagenKnoten,
+    parent_id: Option<Uuid>,
+    sort_order: i64,
+) -> DbResult<Uuid> {
+    let id = Uuid::new_v4();
+    let id_str = id.to_string();
+
+    sqlx::query(
+        "INSERT INTO channels
+         (id, name, parent_id, topic, password_hash, max_clients, is_default, sort_order,
+          channel_type, codec_profile, created_at)
+         VALUES (?, ?, ?, ?, NULL, ?, 0, ?, ?, ?, ?)",
+    )
+    .bind(&id_str)
+    .bind(knoten.name.trim())
+    .bind(parent_id.map(|u| u.to_string()))
+    .bind(knoten.topic.as_deref())
+    .bind(knoten.max_clients)
+    .bind(sort_order)
+    .bind(knoten.channel_type.als_str())
+    .bind(knoten.codec_profile.as_deref())
+    .bind(Utc::now().to_rfc3339())
+    .execute(&mut **tx)
+    .await?;
+
+    for b in &knoten.permissions {
+        let (target_type, target_id) = match &b.target {
+            VorlagenZiel::ChannelDefault => ("channel_default", id_str.clone()),
+            VorlagenZiel::ChannelGroup(name) => (
+                "channel_group",
+                gruppe_aufloesen(tx, "channel_groups", name).await?,
+            ),
+            VorlagenZiel::ServerGroup(name) => (
+                "server_group",
+                gruppe_aufloesen(tx, "server_groups", name).await?,
+            ),
+        };
+        let (value_type, tri_state, int_limit, scope_json) = wert_zu_spalten(&b.wert)?;
+
+        sqlx::query(
+            "INSERT INTO permissions
+               (id, target_type, target_id, permission_key, value_type, tri_state, int_limit, scope_json, channel_id)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(Uuid::new_v4().to_string())
+        .bind(target_type)
+        .bind(&target_id)
+        .bind(&b.permission_key)
+        .bind(value_type)
+        .bind(tri_state)
+        .bind(int_limit)
+        .bind(scope_json)
+        .bind(&id_str)
+        .execute(&mut **tx)
+        .await?;
+    }
+
+    Ok(id)
+}
+
+/// Loest einen Gruppennamen zur ID auf (`tabelle` ist eine feste Konstante)
+async fn gruppe_aufloesen(
+    tx: &mut Transaction<'_, Sqlite>,
+    tabelle: &'static str,
+    name: &str,
+) -> DbResult<String> {
+    let sql = format!("SELECT id FROM {tabelle} WHERE name = ?");
+    let id: Option<String> = sqlx::query_scalar(&sql)
+        .bind(name)
+        .fetch_optional(&mut **tx)
+        .await?;
+    id.ok_or_else(|| DbError::UngueltigeDaten(format!("Gruppe '{name}' existiert nicht")))
+}
+
+fn row_to_vorlage(row: &sqlx::sqlite::SqliteRow) -> DbResult<KanalVorlageRecord> {
+    let id_str: String = row.try_get("id")?;
+    let id = Uuid::parse_str(&id_str)
+        .map_err(|e| DbError::intern(format!("Ungueltige Vorlagen-UUID '{id_str}': {e}")))?;
+
+    let created_by_str: Option<String> = row.try_get("created_by")?;
+    let created_by = created_by_str
+        .as_deref()
+        .map(|s| {
+            Uuid::parse_str(s)
+                .map_err(|e| DbError::intern(format!("Ungueltige created_by UUID '{s}': {e}")))
+        })
+        .transpose()?;
+
+    let created_at_str: String = row.try_get("created_at")?;
+    let created_at = chrono::DateTime::parse_from_rfc3339(&created_at_str)
+        .map_err(|e| DbError::intern(format!("Ungueltige created_at '{created_at_str}': {e}")))?
+        .with_timezone(&Utc);
+
+    let definition: String = row.try_get("definition_json")?;
+    let builtin: i64 = row.try_get("builtin")?;
+
+    Ok(KanalVorlageRecord {
+        id,
+        name: row.try_get("name")?,
+        description: row.try_get("description")?,
+        root: serde_json::from_str(&definition)?,
+        builtin: builtin != 0,
+        created_by,
+        created_at,
+    })
+}
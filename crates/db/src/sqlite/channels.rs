@@ -4,7 +4,7 @@ use chrono::Utc;
 use uuid::Uuid;
 
 use crate::error::DbError;
-use crate::models::{KanalRecord, KanalTyp, KanalUpdate, NeuerKanal};
+use crate::models::{KanalFilter, KanalRecord, KanalTyp, KanalUpdate, NeuerKanal};
 use crate::repository::{ChannelRepository, DbResult};
 use crate::sqlite::pool::SqliteDb;
 
@@ -44,6 +44,10 @@ impl ChannelRepository for SqliteDb {
             is_default: data.is_default,
             sort_order: data.sort_order,
             channel_type: data.channel_type,
+            codec_profile: None,
+            slow_mode_secs: 0,
+            edit_window_secs: None,
+            join_by_approval: false,
             created_at: now,
         })
     }
@@ -51,7 +55,7 @@ impl ChannelRepository for SqliteDb {
     async fn get_by_id(&self, id: Uuid) -> DbResult<Option<KanalRecord>> {
         let row = sqlx::query(
             "SELECT id, name, parent_id, topic, password_hash, max_clients,
-                    is_default, sort_order, channel_type, created_at
+                    is_default, sort_order, channel_type, codec_profile, slow_mode_secs, edit_window_secs, join_by_approval, created_at
              FROM channels WHERE id = ?",
         )
         .bind(id.to_string())
@@ -64,7 +68,7 @@ impl ChannelRepository for SqliteDb {
     async fn list(&self) -> DbResult<Vec<KanalRecord>> {
         let rows = sqlx::query(
             "SELECT id, name, parent_id, topic, password_hash, max_clients,
-                    is_default, sort_order, channel_type, created_at
+                    is_default, sort_order, channel_type, codec_profile, slow_mode_secs, edit_window_secs, join_by_approval, created_at
              FROM channels ORDER BY sort_order, name",
         )
         .fetch_all(&self.pool)
@@ -73,6 +77,39 @@ impl ChannelRepository for SqliteDb {
         rows.iter().map(row_to_kanal).collect()
     }
 
+    async fn list_filtered(&self, filter: KanalFilter) -> DbResult<Vec<KanalRecord>> {
+        // LIMIT -1 = ohne Begrenzung
+        let rows = sqlx::query(
+            "SELECT id, name, parent_id, topic, password_hash, max_clients,
+                    is_default, sort_order, channel_type, codec_profile, slow_mode_secs, edit_window_secs, join_by_approval, created_at
+             FROM channels
+             WHERE (?1 IS NULL OR name LIKE ?1 ESCAPE '\\')
+             ORDER BY sort_order, name, id
+             LIMIT ?2 OFFSET ?3",
+        )
+        .bind(filter.name_muster())
+        .bind(filter.limit.unwrap_or(-1))
+        .bind(filter.offset.unwrap_or(0))
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.iter().map(row_to_kanal).collect()
+    }
+
+    async fn count_filtered(&self, filter: KanalFilter) -> DbResult<i64> {
+        use sqlx::Row as _;
+
+        let row = sqlx::query(
+            "SELECT COUNT(*) as cnt FROM channels
+             WHERE (?1 IS NULL OR name LIKE ?1 ESCAPE '\\')",
+        )
+        .bind(filter.name_muster())
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(row.try_get("cnt")?)
+    }
+
     async fn update(&self, id: Uuid, data: KanalUpdate) -> DbResult<KanalRecord> {
         let mut sets: Vec<String> = Vec::new();
 
@@ -97,6 +134,15 @@ impl ChannelRepository for SqliteDb {
         if data.sort_order.is_some() {
             sets.push("sort_order = ?".into());
         }
+        if data.slow_mode_secs.is_some() {
+            sets.push("slow_mode_secs = ?".into());
+        }
+        if data.edit_window_secs.is_some() {
+            sets.push("edit_window_secs = ?".into());
+        }
+        if data.join_by_approval.is_some() {
+            sets.push("join_by_approval = ?".into());
+        }
 
         if sets.is_empty() {
             return self
@@ -129,6 +175,15 @@ impl ChannelRepository for SqliteDb {
         if let Some(v) = data.sort_order {
             q = q.bind(v);
         }
+        if let Some(v) = data.slow_mode_secs {
+            q = q.bind(v);
+        }
+        if let Some(v) = data.edit_window_secs {
+            q = q.bind(v);
+        }
+        if let Some(v) = data.join_by_approval {
+            q = q.bind(v as i64);
+        }
         q = q.bind(id.to_string());
 
         let affected = q.execute(&self.pool).await?.rows_affected();
@@ -153,7 +208,7 @@ impl ChannelRepository for SqliteDb {
     async fn get_children(&self, parent_id: Uuid) -> DbResult<Vec<KanalRecord>> {
         let rows = sqlx::query(
             "SELECT id, name, parent_id, topic, password_hash, max_clients,
-                    is_default, sort_order, channel_type, created_at
+                    is_default, sort_order, channel_type, codec_profile, slow_mode_secs, edit_window_secs, join_by_approval, created_at
              FROM channels WHERE parent_id = ?
              ORDER BY sort_order, name",
         )
@@ -167,7 +222,7 @@ impl ChannelRepository for SqliteDb {
     async fn get_default(&self) -> DbResult<Option<KanalRecord>> {
         let row = sqlx::query(
             "SELECT id, name, parent_id, topic, password_hash, max_clients,
-                    is_default, sort_order, channel_type, created_at
+                    is_default, sort_order, channel_type, codec_profile, slow_mode_secs, edit_window_secs, join_by_approval, created_at
              FROM channels WHERE is_default = 1 LIMIT 1",
         )
         .fetch_optional(&self.pool)
@@ -202,6 +257,7 @@ pub(crate) fn row_to_kanal(row: &sqlx::sqlite::SqliteRow) -> DbResult<KanalRecor
     let channel_type = typ_str.parse::<KanalTyp>().map_err(DbError::intern)?;
 
     let is_default: i64 = row.try_get("is_default")?;
+    let join_by_approval: i64 = row.try_get("join_by_approval")?;
 
     Ok(KanalRecord {
         id,
@@ -213,6 +269,10 @@ pub(crate) fn row_to_kanal(row: &sqlx::sqlite::SqliteRow) -> DbResult<KanalRecor
         is_default: is_default != 0,
         sort_order: row.try_get("sort_order")?,
         channel_type,
+        codec_profile: row.try_get("codec_profile")?,
+        slow_mode_secs: row.try_get("slow_mode_secs")?,
+        edit_window_secs: row.try_get("edit_window_secs")?,
+        join_by_approval: join_by_approval != 0,
         created_at,
     })
 }
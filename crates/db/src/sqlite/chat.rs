@@ -4,7 +4,9 @@ use chrono::{DateTime, Utc};
 use uuid::Uuid;
 
 use crate::error::DbError;
-use crate::models::{ChatNachrichtRecord, NachrichtenFilter, NachrichtenTyp, NeueNachricht};
+use crate::models::{
+    ChatNachrichtRecord, NachrichtenFilter, NachrichtenSuche, NachrichtenTyp, NeueNachricht,
+};
 use crate::repository::{ChatMessageRepository, DbResult};
 use crate::sqlite::pool::SqliteDb;
 
@@ -43,13 +45,14 @@ impl ChatMessageRepository for SqliteDb {
             created_at: now,
             edited_at: None,
             deleted_at: None,
+            edit_count: 0,
         })
     }
 
     async fn get_by_id(&self, id: Uuid) -> DbResult<Option<ChatNachrichtRecord>> {
         let row = sqlx::query(
             "SELECT id, channel_id, sender_id, content, message_type,
-                    reply_to, created_at, edited_at, deleted_at
+                    reply_to, created_at, edited_at, deleted_at, edit_count
              FROM chat_messages WHERE id = ?",
         )
         .bind(id.to_string())
@@ -67,7 +70,7 @@ impl ChatMessageRepository for SqliteDb {
             let before_str = before.format("%Y-%m-%dT%H:%M:%SZ").to_string();
             sqlx::query(
                 "SELECT id, channel_id, sender_id, content, message_type,
-                         reply_to, created_at, edited_at, deleted_at
+                         reply_to, created_at, edited_at, deleted_at, edit_count
                  FROM chat_messages
                  WHERE channel_id = ? AND created_at < ? AND deleted_at IS NULL
                  ORDER BY created_at DESC
@@ -81,7 +84,7 @@ impl ChatMessageRepository for SqliteDb {
         } else {
             sqlx::query(
                 "SELECT id, channel_id, sender_id, content, message_type,
-                         reply_to, created_at, edited_at, deleted_at
+                         reply_to, created_at, edited_at, deleted_at, edit_count
                  FROM chat_messages
                  WHERE channel_id = ? AND deleted_at IS NULL
                  ORDER BY created_at DESC
@@ -104,7 +107,8 @@ impl ChatMessageRepository for SqliteDb {
         let now_str = Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string();
 
         let affected = sqlx::query(
-            "UPDATE chat_messages SET content = ?, edited_at = ? WHERE id = ? AND deleted_at IS NULL",
+            "UPDATE chat_messages SET content = ?, edited_at = ?, edit_count = edit_count + 1
+             WHERE id = ? AND deleted_at IS NULL",
         )
         .bind(new_content)
         .bind(&now_str)
@@ -137,26 +141,29 @@ impl ChatMessageRepository for SqliteDb {
         Ok(affected > 0)
     }
 
-    async fn search(
-        &self,
-        channel_id: Uuid,
-        query: &str,
-        limit: i64,
-    ) -> DbResult<Vec<ChatNachrichtRecord>> {
-        let pattern = format!("%{}%", query.replace('%', "\\%").replace('_', "\\_"));
-        let channel_str = channel_id.to_string();
+    async fn search(&self, suche: NachrichtenSuche<'_>) -> DbResult<Vec<ChatNachrichtRecord>> {
+        let before_str = suche
+            .before
+            .map(|b| b.format("%Y-%m-%dT%H:%M:%SZ").to_string());
 
         let rows = sqlx::query(
             "SELECT id, channel_id, sender_id, content, message_type,
-                    reply_to, created_at, edited_at, deleted_at
+                    reply_to, created_at, edited_at, deleted_at, edit_count
              FROM chat_messages
              WHERE channel_id = ? AND content LIKE ? ESCAPE '\\' AND deleted_at IS NULL
-             ORDER BY created_at DESC
-             LIMIT ?",
+               AND (? IS NULL OR created_at < ?
+                    OR (created_at = ? AND rowid < (SELECT rowid FROM chat_messages WHERE id = ?)))
+             ORDER BY created_at DESC, rowid DESC
+             LIMIT ? OFFSET ?",
         )
-        .bind(&channel_str)
-        .bind(&pattern)
-        .bind(limit)
+        .bind(suche.channel_id.to_string())
+        .bind(suche.like_muster())
+        .bind(&before_str)
+        .bind(&before_str)
+        .bind(&before_str)
+        .bind(suche.before_id.map(|id| id.to_string()))
+        .bind(suche.limit)
+        .bind(suche.offset)
         .fetch_all(&self.pool)
         .await?;
 
@@ -207,6 +214,7 @@ pub(crate) fn row_to_nachricht(row: &sqlx::sqlite::SqliteRow) -> DbResult<ChatNa
         created_at,
         edited_at,
         deleted_at,
+        edit_count: row.try_get("edit_count")?,
     })
 }
 
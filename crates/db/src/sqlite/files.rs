@@ -4,10 +4,17 @@ use chrono::Utc;
 use uuid::Uuid;
 
 use crate::error::DbError;
-use crate::models::{DateiKontingentRecord, DateiRecord, NeueDatei};
+use crate::models::{
+    DateiKontingentRecord, DateiRecord, DateiZugriffFilter, DateiZugriffRecord, NeueDatei,
+    NeuerDateiZugriff, NeuerUpload, UploadRecord,
+};
 use crate::repository::{DbResult, FileRepository};
+use crate::sqlite::bans::parse_opt_uuid;
 use crate::sqlite::pool::SqliteDb;
 
+/// Zeitformat der Datei-Tabellen (lexikographisch sortierbar)
+const ZEITFORMAT: &str = "%Y-%m-%dT%H:%M:%SZ";
+
 impl FileRepository for SqliteDb {
     async fn create(&self, data: NeueDatei<'_>) -> DbResult<DateiRecord> {
         let id = Uuid::new_v4();
@@ -147,6 +154,272 @@ impl FileRepository for SqliteDb {
 
         Ok(())
     }
+
+    async fn log_access(&self, data: NeuerDateiZugriff) -> DbResult<DateiZugriffRecord> {
+        let id = Uuid::new_v4();
+        let now = Utc::now();
+        let now_str = now.format(ZEITFORMAT).to_string();
+
+        sqlx::query(
+            "INSERT INTO file_access_log
+             (id, file_id, user_id, timestamp, bytes_served, completed)
+             VALUES (?, ?, ?, ?, ?, ?)",
+        )
+        .bind(id.to_string())
+        .bind(data.file_id.to_string())
+        .bind(data.user_id.map(|u| u.to_string()))
+        .bind(&now_str)
+        .bind(data.bytes_served)
+        .bind(data.completed)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(DateiZugriffRecord {
+            id,
+            file_id: data.file_id,
+            user_id: data.user_id,
+            timestamp: now,
+            bytes_served: data.bytes_served,
+            completed: data.completed,
+        })
+    }
+
+    async fn list_access(&self, filter: DateiZugriffFilter) -> DbResult<Vec<DateiZugriffRecord>> {
+        let (where_clause, binds) = zugriff_where_klausel(&filter);
+
+        let limit_clause = filter
+            .limit
+            .map(|l| format!("LIMIT {l}"))
+            .unwrap_or_default();
+        let offset_clause = filter
+            .offset
+            .map(|o| format!("OFFSET {o}"))
+            .unwrap_or_default();
+
+        let sql = format!(
+            "SELECT id, file_id, user_id, timestamp, bytes_served, completed
+             FROM file_access_log
+             {where_clause}
+             ORDER BY timestamp DESC, rowid DESC
+             {limit_clause} {offset_clause}"
+        );
+
+        let mut q = sqlx::query(&sql);
+        for v in &binds {
+            q = q.bind(v);
+        }
+
+        let rows = q.fetch_all(&self.pool).await?;
+        rows.iter().map(row_to_zugriff).collect()
+    }
+
+    async fn count_access(&self, filter: DateiZugriffFilter) -> DbResult<i64> {
+        use sqlx::Row as _;
+
+        let (where_clause, binds) = zugriff_where_klausel(&filter);
+        let sql = format!("SELECT COUNT(*) as cnt FROM file_access_log {where_clause}");
+
+        let mut q = sqlx::query(&sql);
+        for v in &binds {
+            q = q.bind(v);
+        }
+
+        let row = q.fetch_one(&self.pool).await?;
+        Ok(row.try_get("cnt")?)
+    }
+
+    async fn count_access_by_file(&self, file_ids: &[Uuid]) -> DbResult<Vec<(Uuid, i64)>> {
+        use sqlx::Row as _;
+
+        if file_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+        let platzhalter = vec!["?"; file_ids.len()].join(", ");
+        let sql = format!(
+            "SELECT file_id, COUNT(*) as cnt FROM file_access_log
+             WHERE file_id IN ({platzhalter})
+             GROUP BY file_id"
+        );
+
+        let mut q = sqlx::query(&sql);
+        for id in file_ids {
+            q = q.bind(id.to_string());
+        }
+
+        let rows = q.fetch_all(&self.pool).await?;
+        rows.iter()
+            .map(|row| {
+                let file_str: String = row.try_get("file_id")?;
+                let file_id = Uuid::parse_str(&file_str).map_err(|e| {
+                    DbError::intern(format!("Ungueltige file_id UUID '{file_str}': {e}"))
+                })?;
+                Ok((file_id, row.try_get("cnt")?))
+            })
+            .collect()
+    }
+
+    async fn purge_access_before(&self, before: chrono::DateTime<Utc>) -> DbResult<u64> {
+        let affected = sqlx::query("DELETE FROM file_access_log WHERE timestamp < ?")
+            .bind(before.format(ZEITFORMAT).to_string())
+            .execute(&self.pool)
+            .await?
+            .rows_affected();
+
+        Ok(affected)
+    }
+
+    async fn create_upload(&self, data: NeuerUpload<'_>) -> DbResult<UploadRecord> {
+        let id = Uuid::new_v4();
+        let now = Utc::now();
+
+        sqlx::query(
+            "INSERT INTO file_uploads
+             (id, channel_id, uploader_id, filename, mime_type, size_bytes, checksum,
+              created_at, expires_at)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(id.to_string())
+        .bind(data.channel_id.to_string())
+        .bind(data.uploader_id.to_string())
+        .bind(data.filename)
+        .bind(data.mime_type)
+        .bind(data.size_bytes)
+        .bind(data.checksum)
+        .bind(now.format(ZEITFORMAT).to_string())
+        .bind(data.expires_at.format(ZEITFORMAT).to_string())
+        .execute(&self.pool)
+        .await?;
+
+        Ok(UploadRecord {
+            id,
+            channel_id: data.channel_id,
+            uploader_id: data.uploader_id,
+            filename: data.filename.to_string(),
+            mime_type: data.mime_type.to_string(),
+            size_bytes: data.size_bytes,
+            checksum: data.checksum.map(str::to_string),
+            created_at: now,
+            expires_at: data.expires_at,
+        })
+    }
+
+    async fn get_upload(&self, id: Uuid) -> DbResult<Option<UploadRecord>> {
+        let row = sqlx::query(
+            "SELECT id, channel_id, uploader_id, filename, mime_type, size_bytes, checksum,
+                    created_at, expires_at
+             FROM file_uploads WHERE id = ?",
+        )
+        .bind(id.to_string())
+        .fetch_optional(&self.pool)
+        .await?;
+
+        row.map(|r| row_to_upload(&r)).transpose()
+    }
+
+    async fn complete_upload(
+        &self,
+        id: Uuid,
+        storage_path: &str,
+        checksum: &str,
+    ) -> DbResult<Option<DateiRecord>> {
+        let id_str = id.to_string();
+        let mut tx = self.pool.begin().await?;
+
+        let uebernommen = sqlx::query(
+            "INSERT INTO files
+             (id, channel_id, uploader_id, filename, mime_type, size_bytes, storage_path, checksum, created_at)
+             SELECT id, channel_id, uploader_id, filename, mime_type, size_bytes, ?, ?, ?
+             FROM file_uploads WHERE id = ?",
+        )
+        .bind(storage_path)
+        .bind(checksum)
+        .bind(Utc::now().format(ZEITFORMAT).to_string())
+        .bind(&id_str)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+        if uebernommen == 0 {
+            return Ok(None);
+        }
+
+        sqlx::query("DELETE FROM file_uploads WHERE id = ?")
+            .bind(&id_str)
+            .execute(&mut *tx)
+            .await?;
+        let row = sqlx::query(
+            "SELECT id, channel_id, uploader_id, filename, mime_type,
+                    size_bytes, storage_path, checksum, created_at, deleted_at
+             FROM files WHERE id = ?",
+        )
+        .bind(&id_str)
+        .fetch_one(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        row_to_datei(&row).map(Some)
+    }
+
+    async fn purge_uploads_before(&self, before: chrono::DateTime<Utc>) -> DbResult<u64> {
+        let affected = sqlx::query("DELETE FROM file_uploads WHERE expires_at < ?")
+            .bind(before.format(ZEITFORMAT).to_string())
+            .execute(&self.pool)
+            .await?
+            .rows_affected();
+
+        Ok(affected)
+    }
+}
+
+/// Baut die WHERE-Klausel fuer Zugriffsprotokoll-Abfragen samt Bind-Werten
+fn zugriff_where_klausel(filter: &DateiZugriffFilter) -> (String, Vec<String>) {
+    let mut conditions: Vec<&str> = Vec::new();
+    let mut binds: Vec<String> = Vec::new();
+
+    if let Some(file_id) = filter.file_id {
+        conditions.push("file_id = ?");
+        binds.push(file_id.to_string());
+    }
+    if let Some(user_id) = filter.user_id {
+        conditions.push("user_id = ?");
+        binds.push(user_id.to_string());
+    }
+    if let Some(since) = filter.since {
+        conditions.push("timestamp >= ?");
+        binds.push(since.format(ZEITFORMAT).to_string());
+    }
+    if let Some(until) = filter.until {
+        conditions.push("timestamp <= ?");
+        binds.push(until.format(ZEITFORMAT).to_string());
+    }
+
+    let where_clause = if conditions.is_empty() {
+        String::new()
+    } else {
+        format!("WHERE {}", conditions.join(" AND "))
+    };
+
+    (where_clause, binds)
+}
+
+fn row_to_zugriff(row: &sqlx::sqlite::SqliteRow) -> DbResult<DateiZugriffRecord> {
+    use sqlx::Row as _;
+
+    let id_str: String = row.try_get("id")?;
+    let id = Uuid::parse_str(&id_str)
+        .map_err(|e| DbError::intern(format!("Ungueltige Zugriffs-UUID '{id_str}': {e}")))?;
+
+    let file_str: String = row.try_get("file_id")?;
+    let file_id = Uuid::parse_str(&file_str)
+        .map_err(|e| DbError::intern(format!("Ungueltige file_id UUID '{file_str}': {e}")))?;
+
+    Ok(DateiZugriffRecord {
+        id,
+        file_id,
+        user_id: parse_opt_uuid(row, "user_id")?,
+        timestamp: parse_db_timestamp(row.try_get("timestamp")?)?,
+        bytes_served: row.try_get("bytes_served")?,
+        completed: row.try_get("completed")?,
+    })
 }
 
 pub(crate) fn row_to_datei(row: &sqlx::sqlite::SqliteRow) -> DbResult<DateiRecord> {
@@ -183,6 +456,35 @@ pub(crate) fn row_to_datei(row: &sqlx::sqlite::SqliteRow) -> DbResult<DateiRecor
     })
 }
 
+fn row_to_upload(row: &sqlx::sqlite::SqliteRow) -> DbResult<UploadRecord> {
+    use sqlx::Row as _;
+
+    let id_str: String = row.try_get("id")?;
+    let id = Uuid::parse_str(&id_str)
+        .map_err(|e| DbError::intern(format!("Ungueltige Upload-UUID '{id_str}': {e}")))?;
+
+    let channel_str: String = row.try_get("channel_id")?;
+    let channel_id = Uuid::parse_str(&channel_str)
+        .map_err(|e| DbError::intern(format!("Ungueltige channel_id UUID '{channel_str}': {e}")))?;
+
+    let uploader_str: String = row.try_get("uploader_id")?;
+    let uploader_id = Uuid::parse_str(&uploader_str).map_err(|e| {
+        DbError::intern(format!("Ungueltige uploader_id UUID '{uploader_str}': {e}"))
+    })?;
+
+    Ok(UploadRecord {
+        id,
+        channel_id,
+        uploader_id,
+        filename: row.try_get("filename")?,
+        mime_type: row.try_get("mime_type")?,
+        size_bytes: row.try_get("size_bytes")?,
+        checksum: row.try_get("checksum")?,
+        created_at: parse_db_timestamp(row.try_get("created_at")?)?,
+        expires_at: parse_db_timestamp(row.try_get("expires_at")?)?,
+    })
+}
+
 fn parse_db_timestamp(s: String) -> DbResult<chrono::DateTime<Utc>> {
     chrono::DateTime::parse_from_rfc3339(&s)
         .or_else(|_| {
@@ -0,0 +1,239 @@
+//! SQLite-Implementierung des ImportRepository
+
+use chrono::Utc;
+use uuid::Uuid;
+
+use crate::error::DbError;
+use crate::models::{
+    BenutzerRecord, EinladungRecord, ImportBenutzer, ImportServerGruppe, KanalRecord,
+    KanalbaumGrenzen, NeueEinladung, ServerGruppeRecord, VorlagenKnoten,
+};
+use crate::repository::{ChannelRepository, DbResult, ImportRepository};
+use crate::sqlite::channel_templates::teilbaum_anlegen;
+use crate::sqlite::permissions_repo::wert_zu_spalten;
+use crate::sqlite::pool::SqliteDb;
+
+/// Wandelt Eindeutigkeits-Verletzungen in `DbError::Eindeutigkeit` um
+fn eindeutigkeit(e: sqlx::Error, meldung: impl FnOnce() -> String) -> DbError {
+    let msg = e.to_string();
+    if msg.contains("UNIQUE") || msg.contains("unique") {
+        DbError::Eindeutigkeit(meldung())
+    } else {
+        DbError::Sqlx(e)
+    }
+}
+
+impl ImportRepository for SqliteDb {
+    async fn kanaele_importieren(
+        &self,
+        wurzeln: &[VorlagenKnoten],
+        grenzen: KanalbaumGrenzen,
+    ) -> DbResult<Vec<KanalRecord>> {
+        for wurzel in wurzeln {
+            wurzel.pruefen().map_err(DbError::UngueltigeDaten)?;
+            if grenzen.max_tiefe > 0 && wurzel.tiefe() > grenzen.max_tiefe {
+                return Err(DbError::UngueltigeDaten(format!(
+                    "Verschachtelungstiefe {} ueberschreitet das Limit von {}",
+                    wurzel.tiefe(),
+                    grenzen.max_tiefe
+                )));
+            }
+        }
+
+        let mut tx = self.pool.begin().await?;
+
+        let vorhanden: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM channels")
+            .fetch_one(&mut *tx)
+            .await?;
+        let neu: usize = wurzeln.iter().map(VorlagenKnoten::anzahl_kanaele).sum();
+        let gesamt = vorhanden as u64 + neu as u64;
+        if grenzen.max_kanaele > 0 && gesamt > grenzen.max_kanaele as u64 {
+            return Err(DbError::UngueltigeDaten(format!(
+                "Kanal-Limit ueberschritten: {gesamt} > {}",
+                grenzen.max_kanaele
+            )));
+        }
+
+        // Importierte Wurzeln hinter die bestehenden Root-Kanaele sortieren
+        let basis: i64 = sqlx::query_scalar(
+            "SELECT COALESCE(MAX(sort_order) + 1, 0) FROM channels WHERE parent_id IS NULL",
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        let mut ids = Vec::with_capacity(neu);
+        for (i, wurzel) in wurzeln.iter().enumerate() {
+            ids.extend(teilbaum_anlegen(&mut tx, wurzel, None, basis + i as i64).await?);
+        }
+
+        tx.commit().await?;
+
+        let mut kanaele = Vec::with_capacity(ids.len());
+        for id in ids {
+            let kanal = ChannelRepository::get_by_id(self, id)
+                .await?
+                .ok_or_else(|| DbError::intern("Kanal nach Import nicht gefunden"))?;
+            kanaele.push(kanal);
+        }
+        Ok(kanaele)
+    }
+
+    async fn server_gruppen_importieren(
+        &self,
+        gruppen: &[ImportServerGruppe],
+    ) -> DbResult<Vec<ServerGruppeRecord>> {
+        let mut tx = self.pool.begin().await?;
+        let mut angelegt = Vec::with_capacity(gruppen.len());
+
+        for gruppe in gruppen {
+            let id = Uuid::new_v4();
+            let id_str = id.to_string();
+
+            sqlx::query(
+                "INSERT INTO server_groups (id, name, priority, is_default, permissions)
+                 VALUES (?, ?, ?, 0, '{}')",
+            )
+            .bind(&id_str)
+            .bind(&gruppe.name)
+            .bind(gruppe.priority)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| {
+                eindeutigkeit(e, || {
+                    format!("Server-Gruppe '{}' existiert bereits", gruppe.name)
+                })
+            })?;
+
+            for (key, wert) in &gruppe.berechtigungen {
+                let (value_type, tri_state, int_limit, scope_json) = wert_zu_spalten(wert)?;
+                sqlx::query(
+                    "INSERT INTO permissions
+                       (id, target_type, target_id, permission_key, value_type, tri_state, int_limit, scope_json, channel_id)
+                     VALUES (?, 'server_group', ?, ?, ?, ?, ?, ?, NULL)",
+                )
+                .bind(Uuid::new_v4().to_string())
+                .bind(&id_str)
+                .bind(key)
+                .bind(value_type)
+                .bind(tri_state)
+                .bind(int_limit)
+                .bind(scope_json)
+                .execute(&mut *tx)
+                .await?;
+            }
+
+            angelegt.push(ServerGruppeRecord {
+                id,
+                name: gruppe.name.clone(),
+                priority: gruppe.priority,
+                is_default: false,
+                permissions: serde_json::Value::Object(Default::default()),
+            });
+        }
+
+        tx.commit().await?;
+        Ok(angelegt)
+    }
+
+    async fn benutzer_importieren(
+        &self,
+        benutzer: &[ImportBenutzer],
+    ) -> DbResult<Vec<BenutzerRecord>> {
+        let mut tx = self.pool.begin().await?;
+        let mut angelegt = Vec::with_capacity(benutzer.len());
+        let now = Utc::now();
+        let now_str = now.to_rfc3339();
+
+        for b in benutzer {
+            let id = Uuid::new_v4();
+            let id_str = id.to_string();
+
+            sqlx::query(
+                "INSERT INTO users (id, username, password_hash, created_at, is_active, password_changed)
+                 VALUES (?, ?, ?, ?, 1, 0)",
+            )
+            .bind(&id_str)
+            .bind(&b.username)
+            .bind(&b.password_hash)
+            .bind(&now_str)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| {
+                eindeutigkeit(e, || {
+                    format!("Benutzername '{}' bereits vergeben", b.username)
+                })
+            })?;
+
+            for gruppe in &b.gruppen {
+                sqlx::query("INSERT INTO user_server_groups (user_id, group_id) VALUES (?, ?)")
+                    .bind(&id_str)
+                    .bind(gruppe.to_string())
+                    .execute(&mut *tx)
+                    .await?;
+            }
+
+            angelegt.push(BenutzerRecord {
+                id,
+                username: b.username.clone(),
+                password_hash: b.password_hash.clone(),
+                created_at: now,
+                last_login: None,
+                is_active: true,
+                password_changed: false,
+            });
+        }
+
+        tx.commit().await?;
+        Ok(angelegt)
+    }
+
+    async fn einladungen_importieren(
+        &self,
+        einladungen: &[NeueEinladung<'_>],
+    ) -> DbResult<Vec<EinladungRecord>> {
+        let mut tx = self.pool.begin().await?;
+        let mut angelegt = Vec::with_capacity(einladungen.len());
+        let now = Utc::now();
+        let now_str = now.to_rfc3339();
+
+        for data in einladungen {
+            let id = Uuid::new_v4();
+
+            sqlx::query(
+                "INSERT INTO invites
+                   (id, code, channel_id, assigned_group_id, max_uses, used_count, expires_at, created_by, created_at)
+                 VALUES (?, ?, ?, ?, ?, 0, ?, ?, ?)",
+            )
+            .bind(id.to_string())
+            .bind(data.code)
+            .bind(data.channel_id.map(|u| u.to_string()))
+            .bind(data.assigned_group_id.map(|u| u.to_string()))
+            .bind(data.max_uses)
+            .bind(data.expires_at.as_ref().map(|dt| dt.to_rfc3339()))
+            .bind(data.created_by.to_string())
+            .bind(&now_str)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| {
+                eindeutigkeit(e, || {
+                    format!("Einladungscode '{}' bereits vergeben", data.code)
+                })
+            })?;
+
+            angelegt.push(EinladungRecord {
+                id,
+                code: data.code.to_string(),
+                channel_id: data.channel_id,
+                assigned_group_id: data.assigned_group_id,
+                max_uses: data.max_uses,
+                used_count: 0,
+                expires_at: data.expires_at,
+                created_by: data.created_by,
+                created_at: now,
+            });
+        }
+
+        tx.commit().await?;
+        Ok(angelegt)
+    }
+}
@@ -142,11 +142,20 @@ impl InviteRepository for SqliteDb {
             return Err(DbError::EinladungErschoepft);
         }
 
-        // used_count erhoehen
-        sqlx::query("UPDATE invites SET used_count = used_count + 1 WHERE code = ?")
-            .bind(code)
-            .execute(&mut *tx)
-            .await?;
+        // used_count nur erhoehen, solange noch Nutzungen frei sind: parallele
+        // Einloesungen lesen denselben Stand, aber nur eine schreibt
+        let erhoeht = sqlx::query(
+            "UPDATE invites SET used_count = used_count + 1
+             WHERE code = ? AND (max_uses = 0 OR used_count < max_uses)",
+        )
+        .bind(code)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+        if erhoeht == 0 {
+            tx.rollback().await?;
+            return Err(DbError::EinladungErschoepft);
+        }
 
         tx.commit().await?;
 
@@ -154,6 +163,17 @@ impl InviteRepository for SqliteDb {
         self.get_by_code(code).await
     }
 
+    async fn release_invite(&self, code: &str) -> DbResult<bool> {
+        let affected = sqlx::query(
+            "UPDATE invites SET used_count = used_count - 1 WHERE code = ? AND used_count > 0",
+        )
+        .bind(code)
+        .execute(&self.pool)
+        .await?
+        .rows_affected();
+        Ok(affected > 0)
+    }
+
     async fn revoke(&self, id: Uuid) -> DbResult<bool> {
         let affected = sqlx::query("DELETE FROM invites WHERE id = ?")
             .bind(id.to_string())
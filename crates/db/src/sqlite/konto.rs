@@ -0,0 +1,602 @@
+//! SQLite-Implementierung des KontoRepository
+
+use chrono::{DateTime, Utc};
+use sqlx::Row;
+use uuid::Uuid;
+
+use crate::error::DbError;
+use crate::models::{
+    InhaltsBereinigungRecord, InhaltsStapel, KontoDatei, KontoDaten, KontoExportRecord,
+    KontoLoeschAuftrag, KontoLoeschung, KontoNachricht, KontoProfil, NachrichtenRichtlinie,
+    NeueInhaltsBereinigung, NeuerKontoExport, GELOESCHTER_BENUTZER,
+};
+use crate::repository::{DbResult, KontoRepository};
+use crate::sqlite::audit::row_to_audit;
+use crate::sqlite::chat::row_to_nachricht;
+use crate::sqlite::files::row_to_datei;
+use crate::sqlite::pool::SqliteDb;
+use crate::sqlite::users::row_to_benutzer;
+use crate::zeitplan::zeit_als_text;
+
+const EXPORT_SPALTEN: &str =
+    "id, user_id, storage_path, size_bytes, created_at, completed_at, expires_at, downloaded_at";
+
+const BEREINIGUNG_SPALTEN: &str =
+    "id, user_id, since, channel_id, requested_by, messages, files, created_at, completed_at";
+
+impl KontoRepository for SqliteDb {
+    async fn daten_sammeln(&self, user_id: Uuid) -> DbResult<Option<KontoDaten>> {
+        let user_str = user_id.to_string();
+        // Eine Lese-Transaktion: alle Teile stammen aus demselben Stand
+        let mut tx = self.pool.begin().await?;
+
+        let row = sqlx::query(
+            "SELECT id, username, password_hash, created_at, last_login, is_active, password_changed
+             FROM users WHERE id = ?",
+        )
+        .bind(&user_str)
+        .fetch_optional(&mut *tx)
+        .await?;
+        let Some(benutzer) = row.map(|r| row_to_benutzer(&r)).transpose()? else {
+            return Ok(None);
+        };
+
+        let server_gruppen: Vec<String> = sqlx::query_scalar(
+            "SELECT sg.name FROM server_groups sg
+             JOIN user_server_groups usg ON usg.group_id = sg.id
+             WHERE usg.user_id = ?
+             ORDER BY sg.priority DESC, sg.name",
+        )
+        .bind(&user_str)
+        .fetch_all(&mut *tx)
+        .await?;
+
+        let nachrichten = sqlx::query(
+            "SELECT m.id, m.channel_id, m.sender_id, m.content, m.message_type,
+                    m.reply_to, m.created_at, m.edited_at, m.deleted_at, m.edit_count,
+                    c.name AS kanal_name
+             FROM chat_messages m
+             JOIN channels c ON c.id = m.channel_id
+             WHERE m.sender_id = ?
+             ORDER BY m.created_at, m.rowid",
+        )
+        .bind(&user_str)
+        .fetch_all(&mut *tx)
+        .await?
+        .iter()
+        .map(|row| {
+            let n = row_to_nachricht(row)?;
+            Ok(KontoNachricht {
+                id: n.id,
+                channel_id: n.channel_id,
+                kanal_name: row.try_get("kanal_name")?,
+                content: n.content,
+                message_type: n.message_type,
+                reply_to: n.reply_to,
+                created_at: n.created_at,
+                edited_at: n.edited_at,
+                deleted_at: n.deleted_at,
+            })
+        })
+        .collect::<DbResult<Vec<_>>>()?;
+
+        let dateien = sqlx::query(
+            "SELECT f.id, f.channel_id, f.uploader_id, f.filename, f.mime_type, f.size_bytes,
+                    f.storage_path, f.checksum, f.created_at, f.deleted_at, c.name AS kanal_name
+             FROM files f
+             JOIN channels c ON c.id = f.channel_id
+             WHERE f.uploader_id = ?
+             ORDER BY f.created_at, f.rowid",
+        )
+        .bind(&user_str)
+        .fetch_all(&mut *tx)
+        .await?
+        .iter()
+        .map(|row| {
+            let d = row_to_datei(row)?;
+            Ok(KontoDatei {
+                id: d.id,
+                channel_id: d.channel_id,
+                kanal_name: row.try_get("kanal_name")?,
+                filename: d.filename,
+                mime_type: d.mime_type,
+                size_bytes: d.size_bytes,
+                checksum: d.checksum,
+                created_at: d.created_at,
+                deleted_at: d.deleted_at,
+            })
+        })
+        .collect::<DbResult<Vec<_>>>()?;
+
+        let audit = sqlx::query(
+            "SELECT id, actor_id, action, target_type, target_id, details_json, timestamp
+             FROM audit_log WHERE actor_id = ?
+             ORDER BY timestamp, rowid",
+        )
+        .bind(&user_str)
+        .fetch_all(&mut *tx)
+        .await?
+        .iter()
+        .map(row_to_audit)
+        .collect::<DbResult<Vec<_>>>()?;
+
+        tx.commit().await?;
+
+        Ok(Some(KontoDaten {
+            profil: KontoProfil {
+                id: benutzer.id,
+                username: benutzer.username,
+                created_at: benutzer.created_at,
+                last_login: benutzer.last_login,
+                is_active: benutzer.is_active,
+                server_gruppen,
+            },
+            nachrichten,
+            dateien,
+            audit,
+        }))
+    }
+
+    async fn export_anlegen(
+        &self,
+        data: NeuerKontoExport<'_>,
+    ) -> DbResult<Option<KontoExportRecord>> {
+        let user_str = data.user_id.to_string();
+        benutzer_pruefen(self, &user_str).await?;
+
+        let id = Uuid::new_v4();
+        let jetzt = Utc::now();
+        let gesperrt_seit = data.gesperrt_seit.map(zeit_als_text);
+
+        // Pruefen und Anlegen in einer Anweisung, damit gleichzeitige
+        // Anforderungen nicht beide durchkommen
+        let angelegt = sqlx::query(
+            "INSERT INTO account_exports
+             (id, user_id, token_hash, storage_path, created_at, expires_at)
+             SELECT ?, ?, ?, ?, ?, ?
+             WHERE ? IS NULL OR NOT EXISTS (
+                 SELECT 1 FROM account_exports WHERE user_id = ? AND created_at >= ?
+             )",
+        )
+        .bind(id.to_string())
+        .bind(&user_str)
+        .bind(data.token_hash)
+        .bind(data.storage_path)
+        .bind(zeit_als_text(jetzt))
+        .bind(zeit_als_text(data.expires_at))
+        .bind(&gesperrt_seit)
+        .bind(&user_str)
+        .bind(&gesperrt_seit)
+        .execute(&self.pool)
+        .await?
+        .rows_affected();
+
+        if angelegt == 0 {
+            return Ok(None);
+        }
+        export_laden(self, id).await
+    }
+
+    async fn export_abschliessen(&self, id: Uuid, size_bytes: i64) -> DbResult<()> {
+        let affected =
+            sqlx::query("UPDATE account_exports SET size_bytes = ?, completed_at = ? WHERE id = ?")
+                .bind(size_bytes)
+                .bind(zeit_als_text(Utc::now()))
+                .bind(id.to_string())
+                .execute(&self.pool)
+                .await?
+                .rows_affected();
+        if affected == 0 {
+            return Err(DbError::nicht_gefunden(format!("Konto-Export {id}")));
+        }
+        Ok(())
+    }
+
+    async fn export_entfernen(&self, id: Uuid) -> DbResult<()> {
+        sqlx::query("DELETE FROM account_exports WHERE id = ?")
+            .bind(id.to_string())
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    async fn export_einloesen(
+        &self,
+        token_hash: &str,
+        jetzt: DateTime<Utc>,
+    ) -> DbResult<Option<KontoExportRecord>> {
+        let jetzt = zeit_als_text(jetzt);
+        let mut tx = self.pool.begin().await?;
+
+        let affected = sqlx::query(
+            "UPDATE account_exports SET downloaded_at = ?
+             WHERE token_hash = ? AND completed_at IS NOT NULL
+               AND downloaded_at IS NULL AND expires_at > ?",
+        )
+        .bind(&jetzt)
+        .bind(token_hash)
+        .bind(&jetzt)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+        if affected == 0 {
+            return Ok(None);
+        }
+
+        let row = sqlx::query(&format!(
+            "SELECT {EXPORT_SPALTEN} FROM account_exports WHERE token_hash = ?"
+        ))
+        .bind(token_hash)
+        .fetch_one(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        row_to_export(&row).map(Some)
+    }
+
+    async fn exporte_ablaufen(&self, jetzt: DateTime<Utc>) -> DbResult<Vec<KontoExportRecord>> {
+        let jetzt = zeit_als_text(jetzt);
+        let mut tx = self.pool.begin().await?;
+
+        let abgelaufen = sqlx::query(&format!(
+            "SELECT {EXPORT_SPALTEN} FROM account_exports WHERE expires_at <= ?"
+        ))
+        .bind(&jetzt)
+        .fetch_all(&mut *tx)
+        .await?
+        .iter()
+        .map(row_to_export)
+        .collect::<DbResult<Vec<_>>>()?;
+
+        sqlx::query("DELETE FROM account_exports WHERE expires_at <= ?")
+            .bind(&jetzt)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+
+        Ok(abgelaufen)
+    }
+
+    async fn konto_loeschen(&self, auftrag: KontoLoeschAuftrag<'_>) -> DbResult<KontoLoeschung> {
+        if auftrag.user_id == GELOESCHTER_BENUTZER {
+            return Err(DbError::UngueltigeDaten(
+                "Der Platzhalter fuer geloeschte Konten kann nicht geloescht werden".into(),
+            ));
+        }
+        let user_str = auftrag.user_id.to_string();
+        benutzer_pruefen(self, &user_str).await?;
+        let platzhalter = GELOESCHTER_BENUTZER.to_string();
+        let mut ergebnis = KontoLoeschung::default();
+
+        // Phase 1: Nachrichten auf den Platzhalter umschreiben
+        let mut tx = self.pool.begin().await?;
+        let abfrage = match auftrag.richtlinie {
+            NachrichtenRichtlinie::Behalten => {
+                sqlx::query("UPDATE chat_messages SET sender_id = ? WHERE sender_id = ?")
+                    .bind(&platzhalter)
+                    .bind(&user_str)
+            }
+            NachrichtenRichtlinie::Leeren => sqlx::query(
+                "UPDATE chat_messages
+                 SET sender_id = ?, content = '', deleted_at = COALESCE(deleted_at, ?)
+                 WHERE sender_id = ?",
+            )
+            .bind(&platzhalter)
+            .bind(zeit_als_text(Utc::now()))
+            .bind(&user_str),
+        };
+        ergebnis.nachrichten = abfrage.execute(&mut *tx).await?.rows_affected();
+        tx.commit().await?;
+
+        // Phase 2: Dateien loeschen und Kontingent freigeben
+        let mut tx = self.pool.begin().await?;
+        ergebnis.dateien = sqlx::query(
+            "SELECT id, channel_id, uploader_id, filename, mime_type, size_bytes,
+                    storage_path, checksum, created_at, deleted_at
+             FROM files WHERE uploader_id = ?",
+        )
+        .bind(&user_str)
+        .fetch_all(&mut *tx)
+        .await?
+        .iter()
+        .map(row_to_datei)
+        .collect::<DbResult<Vec<_>>>()?;
+        // Weich geloeschte Dateien wurden bereits vom Kontingent abgezogen
+        let belegt: i64 = ergebnis
+            .dateien
+            .iter()
+            .filter(|d| d.deleted_at.is_none())
+            .map(|d| d.size_bytes)
+            .sum();
+        sqlx::query("DELETE FROM files WHERE uploader_id = ?")
+            .bind(&user_str)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query(
+            "UPDATE file_quotas SET current_usage = MAX(0, current_usage - ?) WHERE group_id = ?",
+        )
+        .bind(belegt)
+        .bind(auftrag.kontingent_gruppe)
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        // Phase 3: Berechtigungen und Gruppenmitgliedschaften
+        let mut tx = self.pool.begin().await?;
+        for sql in [
+            "DELETE FROM permissions WHERE target_type = 'user' AND target_id = ?",
+            "DELETE FROM user_server_groups WHERE user_id = ?",
+            "DELETE FROM user_channel_groups WHERE user_id = ?",
+        ] {
+            ergebnis.berechtigungen += sqlx::query(sql)
+                .bind(&user_str)
+                .execute(&mut *tx)
+                .await?
+                .rows_affected();
+        }
+        tx.commit().await?;
+
+        // Phase 4: Benutzer entfernen (Exporte, Tokens, Einladungen und
+        // geplante Aktionen folgen per Fremdschluessel) und protokollieren
+        let mut tx = self.pool.begin().await?;
+        ergebnis.exporte =
+            sqlx::query_scalar("SELECT storage_path FROM account_exports WHERE user_id = ?")
+                .bind(&user_str)
+                .fetch_all(&mut *tx)
+                .await?;
+        sqlx::query("DELETE FROM users WHERE id = ?")
+            .bind(&user_str)
+            .execute(&mut *tx)
+            .await?;
+        // Ohne Benutzer-ID und Namen: nur Ausloeser und Umfang
+        let details = serde_json::json!({
+            "ausloeser": if auftrag.admin_id.is_some() { "admin" } else { "selbst" },
+            "richtlinie": auftrag.richtlinie.als_str(),
+            "nachrichten": ergebnis.nachrichten,
+            "dateien": ergebnis.dateien.len(),
+            "berechtigungen": ergebnis.berechtigungen,
+        });
+        sqlx::query(
+            "INSERT INTO audit_log
+               (id, actor_id, action, target_type, target_id, details_json, timestamp)
+             VALUES (?, ?, 'konto.geloescht', 'user', NULL, ?, ?)",
+        )
+        .bind(Uuid::new_v4().to_string())
+        .bind(auftrag.admin_id.map(|id| id.to_string()))
+        .bind(serde_json::to_string(&details)?)
+        .bind(Utc::now().to_rfc3339())
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        Ok(ergebnis)
+    }
+
+    async fn bereinigung_anlegen(
+        &self,
+        data: NeueInhaltsBereinigung,
+    ) -> DbResult<InhaltsBereinigungRecord> {
+        let id = Uuid::new_v4();
+        sqlx::query(
+            "INSERT INTO content_cleanups
+             (id, user_id, since, channel_id, requested_by, created_at)
+             VALUES (?, ?, ?, ?, ?, ?)",
+        )
+        .bind(id.to_string())
+        .bind(data.user_id.to_string())
+        .bind(zeit_als_text(data.seit))
+        .bind(data.channel_id.map(|c| c.to_string()))
+        .bind(data.angefordert_von.map(|a| a.to_string()))
+        .bind(zeit_als_text(Utc::now()))
+        .execute(&self.pool)
+        .await?;
+
+        bereinigung_pflicht(self, id).await
+    }
+
+    async fn bereinigung_laden(&self, id: Uuid) -> DbResult<Option<InhaltsBereinigungRecord>> {
+        let row = sqlx::query(&format!(
+            "SELECT {BEREINIGUNG_SPALTEN} FROM content_cleanups WHERE id = ?"
+        ))
+        .bind(id.to_string())
+        .fetch_optional(&self.pool)
+        .await?;
+        row.map(|r| row_to_bereinigung(&r)).transpose()
+    }
+
+    async fn bereinigung_stapel(
+        &self,
+        id: Uuid,
+        limit: i64,
+        kontingent_gruppe: &str,
+    ) -> DbResult<InhaltsStapel> {
+        let auftrag = bereinigung_pflicht(self, id).await?;
+        let user_str = auftrag.user_id.to_string();
+        let seit = zeit_als_text(auftrag.seit);
+        let kanal = auftrag.channel_id.map(|c| c.to_string());
+        let jetzt = Utc::now();
+        let jetzt_text = zeit_als_text(jetzt);
+        let mut stapel = InhaltsStapel::default();
+
+        // Nur noch nicht geloeschte Zeilen: ein wiederholter Stapel nach
+        // einem Abbruch findet genau den Rest
+        let mut tx = self.pool.begin().await?;
+        stapel.nachrichten = sqlx::query(
+            "SELECT id, channel_id, sender_id, content, message_type,
+                    reply_to, created_at, edited_at, deleted_at, edit_count
+             FROM chat_messages
+             WHERE sender_id = ? AND created_at >= ? AND deleted_at IS NULL
+               AND (? IS NULL OR channel_id = ?)
+             ORDER BY created_at
+             LIMIT ?",
+        )
+        .bind(&user_str)
+        .bind(&seit)
+        .bind(&kanal)
+        .bind(&kanal)
+        .bind(limit)
+        .fetch_all(&mut *tx)
+        .await?
+        .iter()
+        .map(row_to_nachricht)
+        .collect::<DbResult<Vec<_>>>()?;
+        for nachricht in &mut stapel.nachrichten {
+            sqlx::query("UPDATE chat_messages SET deleted_at = ? WHERE id = ?")
+                .bind(&jetzt_text)
+                .bind(nachricht.id.to_string())
+                .execute(&mut *tx)
+                .await?;
+            nachricht.deleted_at = Some(jetzt);
+        }
+
+        stapel.dateien = sqlx::query(
+            "SELECT id, channel_id, uploader_id, filename, mime_type, size_bytes,
+                    storage_path, checksum, created_at, deleted_at
+             FROM files
+             WHERE uploader_id = ? AND created_at >= ? AND deleted_at IS NULL
+               AND (? IS NULL OR channel_id = ?)
+             ORDER BY created_at
+             LIMIT ?",
+        )
+        .bind(&user_str)
+        .bind(&seit)
+        .bind(&kanal)
+        .bind(&kanal)
+        .bind(limit)
+        .fetch_all(&mut *tx)
+        .await?
+        .iter()
+        .map(row_to_datei)
+        .collect::<DbResult<Vec<_>>>()?;
+        for datei in &mut stapel.dateien {
+            sqlx::query("UPDATE files SET deleted_at = ? WHERE id = ?")
+                .bind(&jetzt_text)
+                .bind(datei.id.to_string())
+                .execute(&mut *tx)
+                .await?;
+            datei.deleted_at = Some(jetzt);
+        }
+        let belegt: i64 = stapel.dateien.iter().map(|d| d.size_bytes).sum();
+        sqlx::query(
+            "UPDATE file_quotas SET current_usage = MAX(0, current_usage - ?) WHERE group_id = ?",
+        )
+        .bind(belegt)
+        .bind(kontingent_gruppe)
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query(
+            "UPDATE content_cleanups SET messages = messages + ?, files = files + ? WHERE id = ?",
+        )
+        .bind(stapel.nachrichten.len() as i64)
+        .bind(stapel.dateien.len() as i64)
+        .bind(id.to_string())
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        Ok(stapel)
+    }
+
+    async fn bereinigung_abschliessen(&self, id: Uuid) -> DbResult<InhaltsBereinigungRecord> {
+        sqlx::query(
+            "UPDATE content_cleanups SET completed_at = COALESCE(completed_at, ?) WHERE id = ?",
+        )
+        .bind(zeit_als_text(Utc::now()))
+        .bind(id.to_string())
+        .execute(&self.pool)
+        .await?;
+        bereinigung_pflicht(self, id).await
+    }
+}
+
+/// Gibt `NichtGefunden` zurueck, wenn der Auftrag nicht existiert
+async fn bereinigung_pflicht(db: &SqliteDb, id: Uuid) -> DbResult<InhaltsBereinigungRecord> {
+    db.bereinigung_laden(id)
+        .await?
+        .ok_or_else(|| DbError::nicht_gefunden(format!("Inhaltsbereinigung {id}")))
+}
+
+/// Gibt `NichtGefunden` zurueck, wenn der Benutzer nicht existiert
+async fn benutzer_pruefen(db: &SqliteDb, user_str: &str) -> DbResult<()> {
+    let vorhanden: Option<i64> = sqlx::query_scalar("SELECT 1 FROM users WHERE id = ?")
+        .bind(user_str)
+        .fetch_optional(&db.pool)
+        .await?;
+    vorhanden
+        .map(|_| ())
+        .ok_or_else(|| DbError::nicht_gefunden(format!("User {user_str}")))
+}
+
+async fn export_laden(db: &SqliteDb, id: Uuid) -> DbResult<Option<KontoExportRecord>> {
+    let row = sqlx::query(&format!(
+        "SELECT {EXPORT_SPALTEN} FROM account_exports WHERE id = ?"
+    ))
+    .bind(id.to_string())
+    .fetch_optional(&db.pool)
+    .await?;
+    row.map(|r| row_to_export(&r)).transpose()
+}
+
+fn zeit_parsen(text: &str, spalte: &str) -> DbResult<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(text)
+        .map(|t| t.with_timezone(&Utc))
+        .map_err(|e| DbError::intern(format!("Ungueltige {spalte} '{text}': {e}")))
+}
+
+fn row_to_export(row: &sqlx::sqlite::SqliteRow) -> DbResult<KontoExportRecord> {
+    let uuid = |spalte: &str| -> DbResult<Uuid> {
+        let text: String = row.try_get(spalte)?;
+        Uuid::parse_str(&text)
+            .map_err(|e| DbError::intern(format!("Ungueltige {spalte} UUID '{text}': {e}")))
+    };
+    let zeit = |spalte: &str| -> DbResult<DateTime<Utc>> {
+        let text: String = row.try_get(spalte)?;
+        zeit_parsen(&text, spalte)
+    };
+    let optionale_zeit = |spalte: &str| -> DbResult<Option<DateTime<Utc>>> {
+        let text: Option<String> = row.try_get(spalte)?;
+        text.map(|t| zeit_parsen(&t, spalte)).transpose()
+    };
+
+    Ok(KontoExportRecord {
+        id: uuid("id")?,
+        user_id: uuid("user_id")?,
+        storage_path: row.try_get("storage_path")?,
+        size_bytes: row.try_get("size_bytes")?,
+        created_at: zeit("created_at")?,
+        completed_at: optionale_zeit("completed_at")?,
+        expires_at: zeit("expires_at")?,
+        downloaded_at: optionale_zeit("downloaded_at")?,
+    })
+}
+
+fn row_to_bereinigung(row: &sqlx::sqlite::SqliteRow) -> DbResult<InhaltsBereinigungRecord> {
+    let uuid = |text: String, spalte: &str| -> DbResult<Uuid> {
+        Uuid::parse_str(&text)
+            .map_err(|e| DbError::intern(format!("Ungueltige {spalte} UUID '{text}': {e}")))
+    };
+    let optionale_uuid = |spalte: &str| -> DbResult<Option<Uuid>> {
+        let text: Option<String> = row.try_get(spalte)?;
+        text.map(|t| uuid(t, spalte)).transpose()
+    };
+    let zeit = |spalte: &str| -> DbResult<DateTime<Utc>> {
+        let text: String = row.try_get(spalte)?;
+        zeit_parsen(&text, spalte)
+    };
+    let completed_at: Option<String> = row.try_get("completed_at")?;
+
+    Ok(InhaltsBereinigungRecord {
+        id: uuid(row.try_get("id")?, "id")?,
+        user_id: uuid(row.try_get("user_id")?, "user_id")?,
+        seit: zeit("since")?,
+        channel_id: optionale_uuid("channel_id")?,
+        angefordert_von: optionale_uuid("requested_by")?,
+        nachrichten: row.try_get("messages")?,
+        dateien: row.try_get("files")?,
+        created_at: zeit("created_at")?,
+        completed_at: completed_at
+            .map(|t| zeit_parsen(&t, "completed_at"))
+            .transpose()?,
+    })
+}
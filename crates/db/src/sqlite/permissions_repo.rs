@@ -5,7 +5,9 @@ use uuid::Uuid;
 
 use crate::error::DbError;
 use crate::models::{BerechtigungsWert, BerechtigungsZiel, EffektiveBerechtigung, TriState};
-use crate::permissions::{berechtigungen_aufloesen, BerechtigungsEingabe};
+use crate::permissions::{
+    berechtigungen_aufloesen, berechtigungen_nachverfolgen, BerechtigungsEingabe, BerechtigungsSpur,
+};
 use crate::repository::{DbResult, PermissionRepository};
 use crate::sqlite::pool::SqliteDb;
 
@@ -128,6 +130,40 @@ impl PermissionRepository for SqliteDb {
         user_id: Uuid,
         channel_id: Uuid,
     ) -> DbResult<Vec<EffektiveBerechtigung>> {
+        let eingabe = self
+            .berechtigungs_eingabe_laden(user_id, channel_id)
+            .await?;
+        let aufgeloest = berechtigungen_aufloesen(&eingabe);
+
+        Ok(aufgeloest
+            .into_values()
+            .map(|a| EffektiveBerechtigung {
+                permission_key: a.permission_key,
+                wert: a.wert,
+                quelle: a.stufe.to_string(),
+            })
+            .collect())
+    }
+
+    async fn resolve_with_trace(
+        &self,
+        user_id: Uuid,
+        channel_id: Uuid,
+    ) -> DbResult<Vec<BerechtigungsSpur>> {
+        let eingabe = self
+            .berechtigungs_eingabe_laden(user_id, channel_id)
+            .await?;
+        Ok(berechtigungen_nachverfolgen(&eingabe))
+    }
+}
+
+impl SqliteDb {
+    /// Laedt die Regeln aller Stufen fuer einen User in einem Kanal
+    async fn berechtigungs_eingabe_laden(
+        &self,
+        user_id: Uuid,
+        channel_id: Uuid,
+    ) -> DbResult<BerechtigungsEingabe> {
         // 1. Individuelle Berechtigungen des Users
         let individual = self
             .get_permissions(&BerechtigungsZiel::Benutzer(user_id), Some(channel_id))
@@ -185,24 +221,13 @@ impl PermissionRepository for SqliteDb {
             .get_permissions(&BerechtigungsZiel::ServerDefault, None)
             .await?;
 
-        let eingabe = BerechtigungsEingabe {
+        Ok(BerechtigungsEingabe {
             individual,
             kanal_gruppe: kanal_gruppe_perms,
             kanal_default,
             server_gruppen,
             server_default,
-        };
-
-        let aufgeloest = berechtigungen_aufloesen(&eingabe);
-
-        Ok(aufgeloest
-            .into_values()
-            .map(|a| EffektiveBerechtigung {
-                permission_key: a.permission_key,
-                wert: a.wert,
-                quelle: a.stufe.to_string(),
-            })
-            .collect())
+        })
     }
 }
 
@@ -231,9 +256,9 @@ fn row_to_permission(row: &sqlx::sqlite::SqliteRow) -> DbResult<(String, Berecht
     Ok((key, wert))
 }
 
-type WertSpalten = (&'static str, Option<i64>, Option<i64>, Option<String>);
+pub(crate) type WertSpalten = (&'static str, Option<i64>, Option<i64>, Option<String>);
 
-fn wert_zu_spalten(wert: &BerechtigungsWert) -> DbResult<WertSpalten> {
+pub(crate) fn wert_zu_spalten(wert: &BerechtigungsWert) -> DbResult<WertSpalten> {
     match wert {
         BerechtigungsWert::TriState(ts) => Ok(("tri_state", ts.to_opt_int(), None, None)),
         BerechtigungsWert::IntLimit(limit) => Ok(("int_limit", None, Some(*limit), None)),
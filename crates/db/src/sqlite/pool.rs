@@ -1,12 +1,20 @@
 //! SQLite Connection Pool mit WAL-Modus
 
+use sqlx::migrate::Migrator;
 use sqlx::sqlite::{SqliteConnectOptions, SqliteJournalMode, SqlitePool, SqlitePoolOptions};
+use std::path::Path;
 use std::str::FromStr;
 use tracing::info;
 
 use crate::error::DbError;
+use crate::migrationen;
 use crate::repository::DatabaseConfig;
 
+pub use crate::migrationen::MigrationsFortschritt;
+
+/// Eingebettete Schema-Migrationen
+static MIGRATIONEN: Migrator = sqlx::migrate!("./migrations");
+
 /// Wrapper um den SQLite Connection Pool
 #[derive(Debug, Clone)]
 pub struct SqliteDb {
@@ -16,6 +24,14 @@ pub struct SqliteDb {
 impl SqliteDb {
     /// Erstellt einen neuen Pool, fuehrt Migrationen aus
     pub async fn oeffnen(config: &DatabaseConfig) -> Result<Self, DbError> {
+        Self::oeffnen_mit_fortschritt(config, |_| {}).await
+    }
+
+    /// Wie [`oeffnen`](Self::oeffnen), meldet aber jede ausstehende Migration
+    pub async fn oeffnen_mit_fortschritt(
+        config: &DatabaseConfig,
+        fortschritt: impl FnMut(MigrationsFortschritt),
+    ) -> Result<Self, DbError> {
         let opts = SqliteConnectOptions::from_str(&config.url)?
             .create_if_missing(true)
             .journal_mode(if config.sqlite_wal {
@@ -33,18 +49,70 @@ impl SqliteDb {
         info!(url = %config.url, wal = config.sqlite_wal, "SQLite-Pool geoeffnet");
 
         let db = Self { pool };
-        db.migrationen_ausfuehren().await?;
+        db.migrationen_mit_fortschritt(fortschritt).await?;
 
         Ok(db)
     }
 
     /// Fuehrt alle ausstehenden Migrationen aus
     pub async fn migrationen_ausfuehren(&self) -> Result<(), DbError> {
-        sqlx::migrate!("./migrations").run(&self.pool).await?;
-        info!("Datenbank-Migrationen abgeschlossen");
+        self.migrationen_mit_fortschritt(|_| {}).await
+    }
+
+    /// Fuehrt alle ausstehenden Migrationen aus und meldet jede vor dem Start
+    pub async fn migrationen_mit_fortschritt(
+        &self,
+        fortschritt: impl FnMut(MigrationsFortschritt),
+    ) -> Result<(), DbError> {
+        let mut conn = self.pool.acquire().await?;
+        migrationen::anwenden(&mut *conn, &MIGRATIONEN, fortschritt).await
+    }
+
+    /// Hoechste Schema-Version, die dieses Binary mitbringt
+    pub fn schema_version_binary() -> i64 {
+        migrationen::hoechste_version(&MIGRATIONEN)
+    }
+
+    /// Hoechste erfolgreich angewendete Schema-Version der Datenbank
+    pub async fn schema_version(&self) -> Result<i64, DbError> {
+        let mut conn = self.pool.acquire().await?;
+        migrationen::angewendete_version(&mut *conn).await
+    }
+
+    /// Schreibt eine konsistente Kopie der Datenbank nach `ziel` (`VACUUM INTO`)
+    ///
+    /// Laeuft im laufenden Betrieb: SQLite liest aus einer Lese-Transaktion,
+    /// gleichzeitige Schreiber sehen die Kopie nicht. Die Zieldatei darf
+    /// noch nicht existieren.
+    pub async fn sichern_nach(&self, ziel: &Path) -> Result<(), DbError> {
+        if ziel.exists() {
+            return Err(DbError::UngueltigeDaten(format!(
+                "Sicherungsziel '{}' existiert bereits",
+                ziel.display()
+            )));
+        }
+        let ziel = ziel
+            .to_str()
+            .ok_or_else(|| DbError::UngueltigeDaten("Sicherungsziel ist kein UTF-8".into()))?;
+        sqlx::query("VACUUM INTO ?")
+            .bind(ziel)
+            .execute(&self.pool)
+            .await?;
+        // In-Memory-Datenbanken schreiben die Kopie ebenfalls in den Speicher
+        if !Path::new(ziel).exists() {
+            return Err(DbError::intern(
+                "Sicherung nicht geschrieben (In-Memory-Datenbank?)",
+            ));
+        }
+        info!(ziel, "Datenbank gesichert");
         Ok(())
     }
 
+    /// Prueft, ob die Datenbank Anfragen beantwortet (Health-Check)
+    pub async fn erreichbar(&self) -> bool {
+        sqlx::query("SELECT 1").execute(&self.pool).await.is_ok()
+    }
+
     /// Gibt den internen Pool zurueck (fuer Tests)
     pub fn pool(&self) -> &SqlitePool {
         &self.pool
@@ -0,0 +1,67 @@
+//! SQLite-Implementierung des ServerStartRepository
+
+use chrono::Utc;
+use sqlx::Row;
+use uuid::Uuid;
+
+use crate::error::DbError;
+use crate::models::{NeuerServerStart, ServerStartRecord};
+use crate::repository::{DbResult, ServerStartRepository};
+use crate::sqlite::pool::SqliteDb;
+
+impl ServerStartRepository for SqliteDb {
+    async fn create(&self, data: NeuerServerStart<'_>) -> DbResult<ServerStartRecord> {
+        let id = Uuid::new_v4();
+        sqlx::query(
+            "INSERT INTO server_starts (id, started_at, version, clean_shutdown, config_hash)
+             VALUES (?, ?, ?, ?, ?)",
+        )
+        .bind(id.to_string())
+        .bind(data.started_at.to_rfc3339())
+        .bind(data.version)
+        .bind(data.clean_shutdown)
+        .bind(data.config_hash)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(ServerStartRecord {
+            id,
+            started_at: data.started_at,
+            version: data.version.to_string(),
+            clean_shutdown: data.clean_shutdown,
+            config_hash: data.config_hash.to_string(),
+        })
+    }
+
+    async fn list_recent(&self, limit: i64) -> DbResult<Vec<ServerStartRecord>> {
+        let rows = sqlx::query(
+            "SELECT id, started_at, version, clean_shutdown, config_hash
+             FROM server_starts
+             ORDER BY started_at DESC, rowid DESC
+             LIMIT ?",
+        )
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.iter()
+            .map(|row| {
+                let id_str: String = row.try_get("id")?;
+                let started_at_str: String = row.try_get("started_at")?;
+                let started_at = chrono::DateTime::parse_from_rfc3339(&started_at_str)
+                    .map_err(|e| {
+                        DbError::intern(format!("Ungueltige started_at '{started_at_str}': {e}"))
+                    })?
+                    .with_timezone(&Utc);
+                Ok(ServerStartRecord {
+                    id: Uuid::parse_str(&id_str)
+                        .map_err(|e| DbError::intern(format!("Ungueltige ID '{id_str}': {e}")))?,
+                    started_at,
+                    version: row.try_get("version")?,
+                    clean_shutdown: row.try_get("clean_shutdown")?,
+                    config_hash: row.try_get("config_hash")?,
+                })
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,68 @@
+//! SQLite-Implementierung des SettingsRepository
+
+use chrono::Utc;
+use sqlx::Row;
+
+use crate::error::DbError;
+use crate::models::EinstellungRecord;
+use crate::repository::{DbResult, SettingsRepository};
+use crate::sqlite::pool::SqliteDb;
+
+impl SettingsRepository for SqliteDb {
+    async fn get(&self, key: &str) -> DbResult<Option<String>> {
+        let wert = sqlx::query_scalar("SELECT value FROM server_settings WHERE key = ?")
+            .bind(key)
+            .fetch_optional(&self.pool)
+            .await?;
+        Ok(wert)
+    }
+
+    async fn set(&self, key: &str, value: &str) -> DbResult<()> {
+        sqlx::query(
+            "INSERT INTO server_settings (key, value, updated_at) VALUES (?, ?, ?)
+             ON CONFLICT(key) DO UPDATE SET value = excluded.value,
+                                            updated_at = excluded.updated_at",
+        )
+        .bind(key)
+        .bind(value)
+        .bind(Utc::now().to_rfc3339())
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    async fn set_default(&self, key: &str, value: &str) -> DbResult<bool> {
+        let affected = sqlx::query(
+            "INSERT OR IGNORE INTO server_settings (key, value, updated_at) VALUES (?, ?, ?)",
+        )
+        .bind(key)
+        .bind(value)
+        .bind(Utc::now().to_rfc3339())
+        .execute(&self.pool)
+        .await?
+        .rows_affected();
+        Ok(affected > 0)
+    }
+
+    async fn list(&self) -> DbResult<Vec<EinstellungRecord>> {
+        let rows = sqlx::query("SELECT key, value, updated_at FROM server_settings ORDER BY key")
+            .fetch_all(&self.pool)
+            .await?;
+
+        rows.iter()
+            .map(|row| {
+                let updated_at_str: String = row.try_get("updated_at")?;
+                let updated_at = chrono::DateTime::parse_from_rfc3339(&updated_at_str)
+                    .map_err(|e| {
+                        DbError::intern(format!("Ungueltige updated_at '{updated_at_str}': {e}"))
+                    })?
+                    .with_timezone(&Utc);
+                Ok(EinstellungRecord {
+                    key: row.try_get("key")?,
+                    value: row.try_get("value")?,
+                    updated_at,
+                })
+            })
+            .collect()
+    }
+}
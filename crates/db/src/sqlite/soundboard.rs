@@ -0,0 +1,182 @@
+//! SQLite-Implementierung des SoundboardRepository
+
+use chrono::{DateTime, Utc};
+use sqlx::Row;
+use uuid::Uuid;
+
+use crate::error::DbError;
+use crate::models::{NeuerSound, SoundRecord};
+use crate::repository::{DbResult, SoundboardRepository};
+use crate::sqlite::pool::SqliteDb;
+
+const SPALTEN: &str =
+    "id, name, channel_id, file_id, frame_ms, duration_ms, created_by, created_at";
+
+impl SoundboardRepository for SqliteDb {
+    async fn create(&self, data: NeuerSound<'_>) -> DbResult<SoundRecord> {
+        let name = data.name.trim();
+        if name.is_empty() {
+            return Err(DbError::UngueltigeDaten(
+                "Soundname darf nicht leer sein".into(),
+            ));
+        }
+        if data.frames.is_empty() || data.frame_ms == 0 {
+            return Err(DbError::UngueltigeDaten("Sound ohne Audio-Frames".into()));
+        }
+        let frames = frames_packen(data.frames)?;
+        let duration_ms = u32::try_from(data.frames.len())
+            .ok()
+            .and_then(|n| n.checked_mul(data.frame_ms))
+            .ok_or_else(|| DbError::UngueltigeDaten("Sound ist zu lang".into()))?;
+
+        let id = Uuid::new_v4();
+        let now = Utc::now();
+        sqlx::query(
+            "INSERT INTO soundboard_sounds
+             (id, name, channel_id, file_id, frame_ms, duration_ms, frames, created_by, created_at)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(id.to_string())
+        .bind(name)
+        .bind(data.channel_id.map(|c| c.to_string()))
+        .bind(data.file_id.map(|f| f.to_string()))
+        .bind(i64::from(data.frame_ms))
+        .bind(i64::from(duration_ms))
+        .bind(frames)
+        .bind(data.created_by.map(|u| u.to_string()))
+        .bind(now.to_rfc3339())
+        .execute(&self.pool)
+        .await
+        .map_err(|e| {
+            let msg = e.to_string();
+            if msg.contains("UNIQUE") || msg.contains("unique") {
+                DbError::Eindeutigkeit(format!("Sound '{name}' existiert bereits"))
+            } else {
+                DbError::Sqlx(e)
+            }
+        })?;
+
+        Ok(SoundRecord {
+            id,
+            name: name.to_string(),
+            channel_id: data.channel_id,
+            file_id: data.file_id,
+            frame_ms: data.frame_ms,
+            duration_ms,
+            created_by: data.created_by,
+            created_at: now,
+        })
+    }
+
+    async fn get_by_id(&self, id: Uuid) -> DbResult<Option<SoundRecord>> {
+        let row = sqlx::query(&format!(
+            "SELECT {SPALTEN} FROM soundboard_sounds WHERE id = ?"
+        ))
+        .bind(id.to_string())
+        .fetch_optional(&self.pool)
+        .await?;
+
+        row.map(|r| row_to_sound(&r)).transpose()
+    }
+
+    async fn list(&self, channel_id: Option<Uuid>) -> DbResult<Vec<SoundRecord>> {
+        let rows = match channel_id {
+            Some(kanal) => {
+                sqlx::query(&format!(
+                    "SELECT {SPALTEN} FROM soundboard_sounds
+                     WHERE channel_id IS NULL OR channel_id = ? ORDER BY name, id"
+                ))
+                .bind(kanal.to_string())
+                .fetch_all(&self.pool)
+                .await?
+            }
+            None => {
+                sqlx::query(&format!(
+                    "SELECT {SPALTEN} FROM soundboard_sounds ORDER BY name, id"
+                ))
+                .fetch_all(&self.pool)
+                .await?
+            }
+        };
+
+        rows.iter().map(row_to_sound).collect()
+    }
+
+    async fn get_frames(&self, id: Uuid) -> DbResult<Option<Vec<Vec<u8>>>> {
+        let blob: Option<Vec<u8>> =
+            sqlx::query_scalar("SELECT frames FROM soundboard_sounds WHERE id = ?")
+                .bind(id.to_string())
+                .fetch_optional(&self.pool)
+                .await?;
+
+        blob.map(|b| frames_entpacken(&b)).transpose()
+    }
+
+    async fn delete(&self, id: Uuid) -> DbResult<bool> {
+        let affected = sqlx::query("DELETE FROM soundboard_sounds WHERE id = ?")
+            .bind(id.to_string())
+            .execute(&self.pool)
+            .await?
+            .rows_affected();
+        Ok(affected > 0)
+    }
+}
+
+/// Frames hintereinander, jeweils mit 2 Byte Laenge (Big Endian) davor
+pub(crate) fn frames_packen(frames: &[Vec<u8>]) -> DbResult<Vec<u8>> {
+    let mut blob = Vec::with_capacity(frames.iter().map(|f| f.len() + 2).sum());
+    for frame in frames {
+        let laenge = u16::try_from(frame.len()).map_err(|_| {
+            DbError::UngueltigeDaten(format!("Frame mit {} Bytes zu gross", frame.len()))
+        })?;
+        blob.extend_from_slice(&laenge.to_be_bytes());
+        blob.extend_from_slice(frame);
+    }
+    Ok(blob)
+}
+
+pub(crate) fn frames_entpacken(mut blob: &[u8]) -> DbResult<Vec<Vec<u8>>> {
+    let mut frames = Vec::new();
+    while !blob.is_empty() {
+        let (laenge, rest) = blob
+            .split_first_chunk::<2>()
+            .ok_or_else(|| DbError::intern("Soundboard-Frames abgeschnitten"))?;
+        let laenge = u16::from_be_bytes(*laenge) as usize;
+        if rest.len() < laenge {
+            return Err(DbError::intern("Soundboard-Frames abgeschnitten"));
+        }
+        let (frame, rest) = rest.split_at(laenge);
+        frames.push(frame.to_vec());
+        blob = rest;
+    }
+    Ok(frames)
+}
+
+fn uuid_parsen(text: &str, spalte: &str) -> DbResult<Uuid> {
+    Uuid::parse_str(text).map_err(|e| DbError::intern(format!("Ungueltige {spalte} '{text}': {e}")))
+}
+
+fn row_to_sound(row: &sqlx::sqlite::SqliteRow) -> DbResult<SoundRecord> {
+    let optionale_uuid = |spalte: &str| -> DbResult<Option<Uuid>> {
+        let text: Option<String> = row.try_get(spalte)?;
+        text.map(|t| uuid_parsen(&t, spalte)).transpose()
+    };
+
+    let id: String = row.try_get("id")?;
+    let frame_ms: i64 = row.try_get("frame_ms")?;
+    let duration_ms: i64 = row.try_get("duration_ms")?;
+    let created_at: String = row.try_get("created_at")?;
+
+    Ok(SoundRecord {
+        id: uuid_parsen(&id, "id")?,
+        name: row.try_get("name")?,
+        channel_id: optionale_uuid("channel_id")?,
+        file_id: optionale_uuid("file_id")?,
+        frame_ms: frame_ms as u32,
+        duration_ms: duration_ms as u32,
+        created_by: optionale_uuid("created_by")?,
+        created_at: DateTime::parse_from_rfc3339(&created_at)
+            .map(|t| t.with_timezone(&Utc))
+            .map_err(|e| DbError::intern(format!("Ungueltige created_at '{created_at}': {e}")))?,
+    })
+}
@@ -4,7 +4,7 @@ use chrono::Utc;
 use uuid::Uuid;
 
 use crate::error::DbError;
-use crate::models::{BenutzerRecord, BenutzerUpdate, NeuerBenutzer};
+use crate::models::{BenutzerRecord, BenutzerUpdate, NeuerBenutzer, GELOESCHTER_BENUTZER};
 use crate::repository::{DbResult, UserRepository};
 use crate::sqlite::pool::SqliteDb;
 
@@ -136,15 +136,19 @@ impl UserRepository for SqliteDb {
     }
 
     async fn list(&self, nur_aktive: bool) -> DbResult<Vec<BenutzerRecord>> {
+        // Der Platzhalter fuer geloeschte Konten ist kein echter Benutzer
         let sql = if nur_aktive {
             "SELECT id, username, password_hash, created_at, last_login, is_active, password_changed
-             FROM users WHERE is_active = 1 ORDER BY username"
+             FROM users WHERE is_active = 1 AND id != ? ORDER BY username"
         } else {
             "SELECT id, username, password_hash, created_at, last_login, is_active, password_changed
-             FROM users ORDER BY username"
+             FROM users WHERE id != ? ORDER BY username"
         };
 
-        let rows = sqlx::query(sql).fetch_all(&self.pool).await?;
+        let rows = sqlx::query(sql)
+            .bind(GELOESCHTER_BENUTZER.to_string())
+            .fetch_all(&self.pool)
+            .await?;
 
         rows.iter().map(row_to_benutzer).collect()
     }
@@ -178,7 +182,7 @@ impl UserRepository for SqliteDb {
     }
 }
 
-fn row_to_benutzer(row: &sqlx::sqlite::SqliteRow) -> DbResult<BenutzerRecord> {
+pub(crate) fn row_to_benutzer(row: &sqlx::sqlite::SqliteRow) -> DbResult<BenutzerRecord> {
     use sqlx::Row as _;
 
     let id_str: String = row.try_get("id")?;
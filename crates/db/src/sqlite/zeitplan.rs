@@ -0,0 +1,174 @@
+//! SQLite-Implementierung des ZeitplanRepository
+
+use chrono::{DateTime, Utc};
+use sqlx::Row;
+use uuid::Uuid;
+
+use crate::error::DbError;
+use crate::models::{GeplanteAktionRecord, NeueGeplanteAktion};
+use crate::repository::{DbResult, ZeitplanRepository};
+use crate::sqlite::pool::SqliteDb;
+use crate::zeitplan::{zeit_als_text, Zeitplan};
+
+const SPALTEN: &str = "id, name, action_json, run_at, cron, time_zone, next_run_at, last_run_at,
+                       created_by, scopes_json, cancelled, created_at";
+
+impl ZeitplanRepository for SqliteDb {
+    async fn create(&self, data: NeueGeplanteAktion<'_>) -> DbResult<GeplanteAktionRecord> {
+        if data.name.trim().is_empty() {
+            return Err(DbError::UngueltigeDaten(
+                "Name der geplanten Aktion darf nicht leer sein".into(),
+            ));
+        }
+
+        let id = Uuid::new_v4();
+        let now = Utc::now();
+        let (run_at, cron) = match &data.zeitplan {
+            Zeitplan::Einmalig(zeitpunkt) => (Some(zeit_als_text(*zeitpunkt)), None),
+            Zeitplan::Wiederkehrend { cron, .. } => (None, Some(cron.als_str())),
+        };
+        let scopes = data.scopes.map(serde_json::to_string).transpose()?;
+
+        sqlx::query(
+            "INSERT INTO scheduled_actions
+             (id, name, action_json, run_at, cron, time_zone, next_run_at, created_by,
+              scopes_json, created_at)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(id.to_string())
+        .bind(data.name.trim())
+        .bind(serde_json::to_string(&data.aktion)?)
+        .bind(run_at)
+        .bind(cron)
+        .bind(data.zeitplan.zeitzone().name())
+        .bind(zeit_als_text(data.next_run_at))
+        .bind(data.created_by.to_string())
+        .bind(scopes)
+        .bind(zeit_als_text(now))
+        .execute(&self.pool)
+        .await?;
+
+        ZeitplanRepository::get_by_id(self, id)
+            .await?
+            .ok_or_else(|| DbError::intern("Geplante Aktion nach dem Anlegen nicht gefunden"))
+    }
+
+    async fn get_by_id(&self, id: Uuid) -> DbResult<Option<GeplanteAktionRecord>> {
+        let row = sqlx::query(&format!(
+            "SELECT {SPALTEN} FROM scheduled_actions WHERE id = ?"
+        ))
+        .bind(id.to_string())
+        .fetch_optional(&self.pool)
+        .await?;
+
+        row.map(|r| row_to_aktion(&r)).transpose()
+    }
+
+    async fn list(&self) -> DbResult<Vec<GeplanteAktionRecord>> {
+        let rows = sqlx::query(&format!(
+            "SELECT {SPALTEN} FROM scheduled_actions ORDER BY created_at, id"
+        ))
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.iter().map(row_to_aktion).collect()
+    }
+
+    async fn cancel(&self, id: Uuid) -> DbResult<bool> {
+        let affected = sqlx::query(
+            "UPDATE scheduled_actions SET cancelled = 1, next_run_at = NULL
+             WHERE id = ? AND cancelled = 0",
+        )
+        .bind(id.to_string())
+        .execute(&self.pool)
+        .await?
+        .rows_affected();
+        Ok(affected > 0)
+    }
+
+    async fn due(&self, jetzt: DateTime<Utc>) -> DbResult<Vec<GeplanteAktionRecord>> {
+        let rows = sqlx::query(&format!(
+            "SELECT {SPALTEN} FROM scheduled_actions
+             WHERE cancelled = 0 AND next_run_at IS NOT NULL AND next_run_at <= ?
+             ORDER BY next_run_at, id"
+        ))
+        .bind(zeit_als_text(jetzt))
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.iter().map(row_to_aktion).collect()
+    }
+
+    async fn claim(
+        &self,
+        id: Uuid,
+        geplant: DateTime<Utc>,
+        naechste: Option<DateTime<Utc>>,
+        jetzt: DateTime<Utc>,
+    ) -> DbResult<bool> {
+        let affected = sqlx::query(
+            "UPDATE scheduled_actions SET next_run_at = ?, last_run_at = ?
+             WHERE id = ? AND cancelled = 0 AND next_run_at = ?",
+        )
+        .bind(naechste.map(zeit_als_text))
+        .bind(zeit_als_text(jetzt))
+        .bind(id.to_string())
+        .bind(zeit_als_text(geplant))
+        .execute(&self.pool)
+        .await?
+        .rows_affected();
+        Ok(affected > 0)
+    }
+}
+
+fn zeit_parsen(text: &str, spalte: &str) -> DbResult<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(text)
+        .map(|t| t.with_timezone(&Utc))
+        .map_err(|e| DbError::intern(format!("Ungueltige {spalte} '{text}': {e}")))
+}
+
+fn row_to_aktion(row: &sqlx::sqlite::SqliteRow) -> DbResult<GeplanteAktionRecord> {
+    let id_str: String = row.try_get("id")?;
+    let id = Uuid::parse_str(&id_str)
+        .map_err(|e| DbError::intern(format!("Ungueltige Zeitplan-UUID '{id_str}': {e}")))?;
+
+    let created_by_str: String = row.try_get("created_by")?;
+    let created_by = Uuid::parse_str(&created_by_str).map_err(|e| {
+        DbError::intern(format!(
+            "Ungueltige created_by UUID '{created_by_str}': {e}"
+        ))
+    })?;
+
+    let run_at: Option<String> = row.try_get("run_at")?;
+    let cron: Option<String> = row.try_get("cron")?;
+    let time_zone: String = row.try_get("time_zone")?;
+    let zeitplan = match (run_at, cron) {
+        (Some(run_at), _) => Zeitplan::Einmalig(zeit_parsen(&run_at, "run_at")?),
+        (None, Some(cron)) => Zeitplan::wiederkehrend(&cron, &time_zone)
+            .map_err(|e| DbError::intern(format!("Zeitplan {id}: {e}")))?,
+        (None, None) => return Err(DbError::intern(format!("Zeitplan {id} ohne Zeitpunkt"))),
+    };
+
+    let optionale_zeit = |spalte: &str| -> DbResult<Option<DateTime<Utc>>> {
+        let text: Option<String> = row.try_get(spalte)?;
+        text.map(|t| zeit_parsen(&t, spalte)).transpose()
+    };
+
+    let action_json: String = row.try_get("action_json")?;
+    let scopes_json: Option<String> = row.try_get("scopes_json")?;
+    let created_at: String = row.try_get("created_at")?;
+    let cancelled: i64 = row.try_get("cancelled")?;
+
+    Ok(GeplanteAktionRecord {
+        id,
+        name: row.try_get("name")?,
+        aktion: serde_json::from_str(&action_json)?,
+        zeitplan,
+        next_run_at: optionale_zeit("next_run_at")?,
+        last_run_at: optionale_zeit("last_run_at")?,
+        created_by,
+        scopes: scopes_json.map(|s| serde_json::from_str(&s)).transpose()?,
+        cancelled: cancelled != 0,
+        created_at: zeit_parsen(&created_at, "created_at")?,
+    })
+}
@@ -0,0 +1,232 @@
+//! Zeitlimits fuer Repository-Aufrufe
+//!
+//! Eine haengende Schreiboperation (z.B. gesperrte SQLite-Datei) darf
+//! Anfragen nicht unbegrenzt blockieren. Aufrufer ordnen ihre Arbeit einer
+//! [`Zugriffsart`] zu und begrenzen sie mit [`begrenzen`]:
+//!
+//! - **Lesen** laeuft inline – bei Zeitueberschreitung oder Abbruch durch den
+//!   Aufrufer wird die Future verworfen und die Arbeit endet sofort.
+//! - **Schreiben** sollte vorher als eigener Task gestartet werden, sodass nur
+//!   das Warten auf das Ergebnis begrenzt wird. Die Operation laeuft dann zu
+//!   Ende und ihr Ergebnis wird verworfen – so entsteht kein Teilzustand.
+//!
+//! Zeitueberschreitungen und abgebrochene Aufrufe werden in
+//! [`ZeitlimitMetriken`] gezaehlt.
+
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+/// Standard-Zeitlimit fuer lesende Aufrufe
+pub const STANDARD_LESEN: Duration = Duration::from_secs(2);
+/// Standard-Zeitlimit fuer schreibende Aufrufe
+pub const STANDARD_SCHREIBEN: Duration = Duration::from_secs(5);
+
+/// Art eines Repository-Zugriffs
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Zugriffsart {
+    Lesen,
+    Schreiben,
+}
+
+impl Zugriffsart {
+    pub fn als_str(&self) -> &'static str {
+        match self {
+            Self::Lesen => "lesen",
+            Self::Schreiben => "schreiben",
+        }
+    }
+}
+
+impl std::fmt::Display for Zugriffsart {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.als_str())
+    }
+}
+
+/// Zeitlimits pro Zugriffsart
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Zeitlimits {
+    pub lesen: Duration,
+    pub schreiben: Duration,
+}
+
+impl Default for Zeitlimits {
+    fn default() -> Self {
+        Self {
+            lesen: STANDARD_LESEN,
+            schreiben: STANDARD_SCHREIBEN,
+        }
+    }
+}
+
+impl Zeitlimits {
+    /// Gibt das Zeitlimit fuer eine Zugriffsart zurueck
+    pub fn fuer(&self, art: Zugriffsart) -> Duration {
+        match art {
+            Zugriffsart::Lesen => self.lesen,
+            Zugriffsart::Schreiben => self.schreiben,
+        }
+    }
+}
+
+/// Ein Aufruf hat sein Zeitlimit ueberschritten
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+#[error("Zeitueberschreitung beim {art} nach {} ms", limit.as_millis())]
+pub struct Zeitueberschreitung {
+    pub art: Zugriffsart,
+    pub limit: Duration,
+}
+
+/// Zaehler fuer Zeitueberschreitungen und abgebrochene Aufrufe
+#[derive(Debug, Default)]
+pub struct ZeitlimitMetriken {
+    zeitueberschreitungen: [AtomicU64; 2],
+    abgebrochen: [AtomicU64; 2],
+}
+
+/// Momentaufnahme der [`ZeitlimitMetriken`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ZeitlimitStatistik {
+    pub zeitueberschreitungen_lesen: u64,
+    pub zeitueberschreitungen_schreiben: u64,
+    pub abgebrochen_lesen: u64,
+    pub abgebrochen_schreiben: u64,
+}
+
+impl ZeitlimitMetriken {
+    pub fn neu() -> Self {
+        Self::default()
+    }
+
+    /// Gibt die aktuellen Zaehlerstaende zurueck
+    pub fn statistik(&self) -> ZeitlimitStatistik {
+        let lesen = |z: &[AtomicU64; 2], art: Zugriffsart| z[art as usize].load(Ordering::Relaxed);
+        ZeitlimitStatistik {
+            zeitueberschreitungen_lesen: lesen(&self.zeitueberschreitungen, Zugriffsart::Lesen),
+            zeitueberschreitungen_schreiben: lesen(
+                &self.zeitueberschreitungen,
+                Zugriffsart::Schreiben,
+            ),
+            abgebrochen_lesen: lesen(&self.abgebrochen, Zugriffsart::Lesen),
+            abgebrochen_schreiben: lesen(&self.abgebrochen, Zugriffsart::Schreiben),
+        }
+    }
+
+    fn zeitueberschreitung(&self, art: Zugriffsart) {
+        self.zeitueberschreitungen[art as usize].fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn abgebrochen(&self, art: Zugriffsart) {
+        self.abgebrochen[art as usize].fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// Zaehlt einen Abbruch, falls die begrenzte Future vorzeitig verworfen wird
+struct AbbruchWaechter<'a> {
+    metriken: &'a ZeitlimitMetriken,
+    art: Zugriffsart,
+    beendet: bool,
+}
+
+impl Drop for AbbruchWaechter<'_> {
+    fn drop(&mut self) {
+        if !self.beendet {
+            self.metriken.abgebrochen(self.art);
+            tracing::debug!(art = %self.art, "Aufruf vom Aufrufer abgebrochen");
+        }
+    }
+}
+
+/// Begrenzt `arbeit` auf das Zeitlimit der Zugriffsart
+///
+/// Wird die zurueckgegebene Future vor Abschluss verworfen (z.B. weil der
+/// Client die Verbindung getrennt hat), zaehlt das als Abbruch.
+pub async fn begrenzen<F: Future>(
+    art: Zugriffsart,
+    limits: &Zeitlimits,
+    metriken: &ZeitlimitMetriken,
+    arbeit: F,
+) -> Result<F::Output, Zeitueberschreitung> {
+    let limit = limits.fuer(art);
+    let mut waechter = AbbruchWaechter {
+        metriken,
+        art,
+        beendet: false,
+    };
+    let ergebnis = tokio::time::timeout(limit, arbeit).await;
+    waechter.beendet = true;
+
+    ergebnis.map_err(|_| {
+        metriken.zeitueberschreitung(art);
+        tracing::warn!(art = %art, limit_ms = limit.as_millis() as u64, "Zeitlimit ueberschritten");
+        Zeitueberschreitung { art, limit }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test(start_paused = true)]
+    async fn schnelle_arbeit_liefert_ergebnis() {
+        let metriken = ZeitlimitMetriken::neu();
+        let ergebnis = begrenzen(
+            Zugriffsart::Lesen,
+            &Zeitlimits::default(),
+            &metriken,
+            async {
+                tokio::time::sleep(Duration::from_millis(500)).await;
+                7
+            },
+        )
+        .await;
+        assert_eq!(ergebnis, Ok(7));
+        assert_eq!(metriken.statistik(), ZeitlimitStatistik::default());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn zeitlimit_pro_zugriffsart() {
+        let metriken = ZeitlimitMetriken::neu();
+        let limits = Zeitlimits::default();
+        let langsam = || tokio::time::sleep(Duration::from_secs(3));
+
+        let lesen = begrenzen(Zugriffsart::Lesen, &limits, &metriken, langsam()).await;
+        assert_eq!(
+            lesen,
+            Err(Zeitueberschreitung {
+                art: Zugriffsart::Lesen,
+                limit: STANDARD_LESEN,
+            })
+        );
+        // 3 s liegen innerhalb des Schreib-Limits
+        assert!(
+            begrenzen(Zugriffsart::Schreiben, &limits, &metriken, langsam())
+                .await
+                .is_ok()
+        );
+
+        let statistik = metriken.statistik();
+        assert_eq!(statistik.zeitueberschreitungen_lesen, 1);
+        assert_eq!(statistik.zeitueberschreitungen_schreiben, 0);
+        assert_eq!(statistik.abgebrochen_lesen, 0);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn verworfene_future_zaehlt_als_abbruch() {
+        let metriken = ZeitlimitMetriken::neu();
+        let limits = Zeitlimits::default();
+        let begrenzt = begrenzen(
+            Zugriffsart::Schreiben,
+            &limits,
+            &metriken,
+            std::future::pending::<()>(),
+        );
+        // Nur kurz pollen, dann wie bei einem Verbindungsabbruch verwerfen
+        let _ = tokio::time::timeout(Duration::from_millis(10), begrenzt).await;
+
+        let statistik = metriken.statistik();
+        assert_eq!(statistik.abgebrochen_schreiben, 1);
+        assert_eq!(statistik.zeitueberschreitungen_schreiben, 0);
+    }
+}
@@ -0,0 +1,354 @@
+//! Zeitplaene fuer geplante Aktionen
+//!
+//! Ein [`Zeitplan`] ist entweder ein einmaliger Zeitpunkt (UTC) oder ein
+//! Cron-Ausdruck mit fuenf Feldern (`Minute Stunde Tag Monat Wochentag`),
+//! der in einer expliziten IANA-Zeitzone ausgewertet wird. Gespeichert wird
+//! immer UTC; die Zeitzone bestimmt nur, wann "jeden Freitag um 20:00" ist.
+//!
+//! Zeitumstellung: Ortszeiten, die es am Tag der Umstellung nicht gibt
+//! (Sprung vorwaerts), werden uebersprungen. Doppelt vorkommende Ortszeiten
+//! (Sprung zurueck) werden nur beim ersten Auftreten ausgefuehrt.
+
+use std::fmt;
+use std::str::FromStr;
+
+use chrono::{
+    DateTime, Datelike, Duration, LocalResult, NaiveDate, NaiveDateTime, NaiveTime, TimeZone,
+    Timelike, Utc,
+};
+use chrono_tz::Tz;
+
+use crate::error::DbError;
+use crate::repository::DbResult;
+
+/// So viele Tage wird hoechstens nach der naechsten Ausfuehrung gesucht
+/// (deckt auch "29. Februar" ab)
+const MAX_SUCHTAGE: i64 = 366 * 8;
+
+/// Ein Feld eines Cron-Ausdrucks als Bitmaske erlaubter Werte
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct CronFeld {
+    werte: u64,
+    /// Feld war `*` (fuer die Verknuepfung von Tag und Wochentag)
+    alle: bool,
+}
+
+impl CronFeld {
+    fn parsen(text: &str, min: u32, max: u32, name: &str) -> Result<Self, String> {
+        let mut werte = 0u64;
+        for teil in text.split(',') {
+            let (bereich, schritt) = match teil.split_once('/') {
+                Some((b, s)) => {
+                    let schritt: u32 = s
+                        .parse()
+                        .map_err(|_| format!("{name}: ungueltige Schrittweite '{s}'"))?;
+                    if schritt == 0 {
+                        return Err(format!("{name}: Schrittweite darf nicht 0 sein"));
+                    }
+                    (b, schritt)
+                }
+                None => (teil, 1),
+            };
+            let (von, bis) = if bereich == "*" {
+                (min, max)
+            } else if let Some((a, b)) = bereich.split_once('-') {
+                (wert_parsen(a, name)?, wert_parsen(b, name)?)
+            } else {
+                let wert = wert_parsen(bereich, name)?;
+                // "5/15" laeuft wie bei cron ab 5 bis zum Maximum
+                (wert, if teil.contains('/') { max } else { wert })
+            };
+            if von < min || bis > max || von > bis {
+                return Err(format!("{name}: '{teil}' liegt ausserhalb von {min}-{max}"));
+            }
+            for wert in (von..=bis).step_by(schritt as usize) {
+                werte |= 1 << wert;
+            }
+        }
+        Ok(Self {
+            werte,
+            alle: text == "*",
+        })
+    }
+
+    fn enthaelt(&self, wert: u32) -> bool {
+        self.werte & (1 << wert) != 0
+    }
+
+    fn werte(&self) -> impl Iterator<Item = u32> + '_ {
+        (0..64).filter(|w| self.enthaelt(*w))
+    }
+}
+
+fn wert_parsen(text: &str, name: &str) -> Result<u32, String> {
+    text.parse()
+        .map_err(|_| format!("{name}: ungueltiger Wert '{text}'"))
+}
+
+/// Cron-Ausdruck mit fuenf Feldern
+///
+/// Unterstuetzt `*`, Listen (`1,15`), Bereiche (`1-5`) und Schritte (`*/10`).
+/// Wochentag 0 und 7 sind Sonntag. Sind Tag und Wochentag beide
+/// eingeschraenkt, genuegt wie bei cron eines von beiden.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CronAusdruck {
+    text: String,
+    minuten: CronFeld,
+    stunden: CronFeld,
+    tage: CronFeld,
+    monate: CronFeld,
+    wochentage: CronFeld,
+}
+
+impl CronAusdruck {
+    /// Der Ausdruck wie angegeben (normalisierte Leerzeichen)
+    pub fn als_str(&self) -> &str {
+        &self.text
+    }
+
+    fn tag_passt(&self, datum: NaiveDate) -> bool {
+        if !self.monate.enthaelt(datum.month()) {
+            return false;
+        }
+        let tag = self.tage.enthaelt(datum.day());
+        let wochentag = self
+            .wochentage
+            .enthaelt(datum.weekday().num_days_from_sunday());
+        match (self.tage.alle, self.wochentage.alle) {
+            (false, false) => tag || wochentag,
+            _ => tag && wochentag,
+        }
+    }
+}
+
+impl FromStr for CronAusdruck {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let felder: Vec<&str> = text.split_whitespace().collect();
+        let [minute, stunde, tag, monat, wochentag] = felder[..] else {
+            return Err(format!(
+                "Cron-Ausdruck braucht 5 Felder, gefunden: {}",
+                felder.len()
+            ));
+        };
+        let mut wochentage = CronFeld::parsen(wochentag, 0, 7, "Wochentag")?;
+        // 7 ist ebenfalls Sonntag
+        if wochentage.enthaelt(7) {
+            wochentage.werte = (wochentage.werte & !(1 << 7)) | 1;
+        }
+        Ok(Self {
+            text: felder.join(" "),
+            minuten: CronFeld::parsen(minute, 0, 59, "Minute")?,
+            stunden: CronFeld::parsen(stunde, 0, 23, "Stunde")?,
+            tage: CronFeld::parsen(tag, 1, 31, "Tag")?,
+            monate: CronFeld::parsen(monat, 1, 12, "Monat")?,
+            wochentage,
+        })
+    }
+}
+
+impl fmt::Display for CronAusdruck {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.text)
+    }
+}
+
+/// Wann eine geplante Aktion ausgefuehrt wird
+#[derive(Debug, Clone, PartialEq)]
+pub enum Zeitplan {
+    /// Genau einmal zum angegebenen Zeitpunkt
+    Einmalig(DateTime<Utc>),
+    /// Wiederkehrend nach Cron-Ausdruck, ausgewertet in `zeitzone`
+    Wiederkehrend { cron: CronAusdruck, zeitzone: Tz },
+}
+
+impl Zeitplan {
+    /// Wiederkehrender Zeitplan aus Cron-Ausdruck und IANA-Zeitzone
+    /// (z.B. `Europe/Berlin`)
+    pub fn wiederkehrend(ausdruck: &str, zeitzone: &str) -> DbResult<Self> {
+        let cron = ausdruck.parse().map_err(DbError::UngueltigeDaten)?;
+        let zeitzone = zeitzone
+            .parse::<Tz>()
+            .map_err(|_| DbError::UngueltigeDaten(format!("Unbekannte Zeitzone '{zeitzone}'")))?;
+        Ok(Self::Wiederkehrend { cron, zeitzone })
+    }
+
+    /// Zeitzone der Wiederholung (`UTC` bei einmaligen Zeitplaenen)
+    pub fn zeitzone(&self) -> Tz {
+        match self {
+            Self::Einmalig(_) => Tz::UTC,
+            Self::Wiederkehrend { zeitzone, .. } => *zeitzone,
+        }
+    }
+
+    /// Erste Ausfuehrung fuer einen neu angelegten Zeitplan
+    ///
+    /// Einmalige Zeitpunkte muessen in der Zukunft liegen; ein Cron-Ausdruck,
+    /// der nie zutrifft (z.B. `0 0 30 2 *`), ist ungueltig.
+    pub fn erste_ausfuehrung(&self, jetzt: DateTime<Utc>) -> DbResult<DateTime<Utc>> {
+        self.naechste_ausfuehrung(jetzt).ok_or_else(|| {
+            DbError::UngueltigeDaten(match self {
+                Self::Einmalig(zeitpunkt) => {
+                    format!("Zeitpunkt {zeitpunkt} liegt in der Vergangenheit")
+                }
+                Self::Wiederkehrend { cron, .. } => {
+                    format!("Cron-Ausdruck '{cron}' trifft nie zu")
+                }
+            })
+        })
+    }
+
+    /// Naechste Ausfuehrung strikt nach `nach`; `None` wenn keine mehr folgt
+    pub fn naechste_ausfuehrung(&self, nach: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        match self {
+            Self::Einmalig(zeitpunkt) => Some(*zeitpunkt).filter(|z| *z > nach),
+            Self::Wiederkehrend { cron, zeitzone } => naechste_cron(cron, *zeitzone, nach),
+        }
+    }
+}
+
+fn naechste_cron(cron: &CronAusdruck, zeitzone: Tz, nach: DateTime<Utc>) -> Option<DateTime<Utc>> {
+    let start = nach.with_timezone(&zeitzone).naive_local();
+    let mut datum = start.date();
+    for _ in 0..MAX_SUCHTAGE {
+        if cron.tag_passt(datum) {
+            for stunde in cron.stunden.werte() {
+                for minute in cron.minuten.werte() {
+                    let zeit = NaiveTime::from_hms_opt(stunde, minute, 0)?;
+                    let lokal = NaiveDateTime::new(datum, zeit);
+                    if lokal <= start {
+                        continue;
+                    }
+                    let treffer = match zeitzone.from_local_datetime(&lokal) {
+                        LocalResult::Single(t) => t,
+                        // Sprung zurueck: nur das erste Auftreten
+                        LocalResult::Ambiguous(frueh, _) => frueh,
+                        // Sprung vorwaerts: diese Ortszeit gibt es nicht
+                        LocalResult::None => continue,
+                    };
+                    let utc = treffer.with_timezone(&Utc);
+                    if utc > nach {
+                        return Some(utc);
+                    }
+                }
+            }
+        }
+        datum = datum.checked_add_signed(Duration::days(1))?;
+    }
+    None
+}
+
+/// Zeitpunkt als Text fuer die Datenbank (UTC, sekundengenau)
+///
+/// Feste Breite, damit Textvergleiche in SQL der zeitlichen Ordnung folgen.
+pub fn zeit_als_text(zeit: DateTime<Utc>) -> String {
+    zeit.with_nanosecond(0)
+        .unwrap_or(zeit)
+        .format("%Y-%m-%dT%H:%M:%SZ")
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utc(text: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(text)
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    #[test]
+    fn cron_felder_parsen() {
+        let cron: CronAusdruck = "*/15 8-10 * * 1,3,5".parse().unwrap();
+        assert_eq!(
+            cron.minuten.werte().collect::<Vec<_>>(),
+            vec![0, 15, 30, 45]
+        );
+        assert_eq!(cron.stunden.werte().collect::<Vec<_>>(), vec![8, 9, 10]);
+        assert!(cron.wochentage.enthaelt(5));
+
+        let sonntag: CronAusdruck = "0 0 * * 7".parse().unwrap();
+        assert!(sonntag.wochentage.enthaelt(0));
+
+        for falsch in [
+            "* * * *",
+            "60 * * * *",
+            "* * 0 * *",
+            "*/0 * * * *",
+            "5-1 * * * *",
+        ] {
+            assert!(falsch.parse::<CronAusdruck>().is_err(), "{falsch}");
+        }
+    }
+
+    #[test]
+    fn woechentlich_in_zeitzone() {
+        // Freitag 20:00 Berlin = 18:00 UTC im Sommer, 19:00 UTC im Winter
+        let plan = Zeitplan::wiederkehrend("0 20 * * 5", "Europe/Berlin").unwrap();
+        let erste = plan
+            .naechste_ausfuehrung(utc("2026-10-14T12:00:00Z"))
+            .unwrap();
+        assert_eq!(erste, utc("2026-10-16T18:00:00Z"));
+        let zweite = plan.naechste_ausfuehrung(erste).unwrap();
+        assert_eq!(zweite, utc("2026-10-23T18:00:00Z"));
+        // Nach der Zeitumstellung am 25.10.
+        let dritte = plan.naechste_ausfuehrung(zweite).unwrap();
+        assert_eq!(dritte, utc("2026-10-30T19:00:00Z"));
+    }
+
+    #[test]
+    fn zeitumstellung_ueberspringt_und_dedupliziert() {
+        // 02:30 gibt es am 29.03.2026 in Berlin nicht
+        let plan = Zeitplan::wiederkehrend("30 2 * * *", "Europe/Berlin").unwrap();
+        let naechste = plan
+            .naechste_ausfuehrung(utc("2026-03-28T02:00:00Z"))
+            .unwrap();
+        assert_eq!(naechste, utc("2026-03-30T00:30:00Z"));
+
+        // 02:30 gibt es am 25.10.2026 zweimal – nur das erste zaehlt
+        let erste = plan
+            .naechste_ausfuehrung(utc("2026-10-24T12:00:00Z"))
+            .unwrap();
+        assert_eq!(erste, utc("2026-10-25T00:30:00Z"));
+        let danach = plan.naechste_ausfuehrung(erste).unwrap();
+        assert_eq!(danach, utc("2026-10-26T01:30:00Z"));
+    }
+
+    #[test]
+    fn tag_oder_wochentag() {
+        // Am 1. des Monats ODER montags
+        let plan = Zeitplan::wiederkehrend("0 12 1 * 1", "UTC").unwrap();
+        let naechste = plan
+            .naechste_ausfuehrung(utc("2026-10-16T00:00:00Z"))
+            .unwrap();
+        assert_eq!(naechste, utc("2026-10-19T12:00:00Z"));
+    }
+
+    #[test]
+    fn einmalig_und_ungueltige_plaene() {
+        let jetzt = utc("2026-10-16T12:00:00Z");
+        let plan = Zeitplan::Einmalig(utc("2026-10-16T13:00:00Z"));
+        assert_eq!(
+            plan.erste_ausfuehrung(jetzt).unwrap(),
+            utc("2026-10-16T13:00:00Z")
+        );
+        assert!(plan
+            .naechste_ausfuehrung(utc("2026-10-16T13:00:00Z"))
+            .is_none());
+
+        let vergangen = Zeitplan::Einmalig(utc("2026-10-16T11:00:00Z"));
+        assert!(vergangen.erste_ausfuehrung(jetzt).is_err());
+
+        let nie = Zeitplan::wiederkehrend("0 0 30 2 *", "UTC").unwrap();
+        assert!(nie.erste_ausfuehrung(jetzt).is_err());
+        assert!(Zeitplan::wiederkehrend("0 0 * * *", "Mars/Olympus").is_err());
+    }
+
+    #[test]
+    fn zeit_als_text_ist_sortierbar() {
+        let zeit = utc("2026-10-16T08:05:09.123Z");
+        assert_eq!(zeit_als_text(zeit), "2026-10-16T08:05:09Z");
+    }
+}
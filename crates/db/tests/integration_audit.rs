@@ -1,7 +1,7 @@
 //! Integration-Tests fuer AuditLogRepository (In-Memory SQLite)
 
 use speakeasy_db::{
-    models::{AuditLogFilter, NeuerBenutzer},
+    models::{AuditLogFilter, NeuerAuditEintrag, NeuerBenutzer},
     AuditLogRepository, SqliteDb, UserRepository,
 };
 
@@ -247,3 +247,35 @@ async fn audit_ereignisse_zaehlen() {
 
     assert_eq!(anzahl, 7);
 }
+
+#[tokio::test]
+async fn sammel_schreiben_in_einer_transaktion() {
+    let db = db().await;
+
+    let eintraege: Vec<NeuerAuditEintrag> = (0..3)
+        .map(|nr| {
+            NeuerAuditEintrag::neu(
+                None,
+                "kanal.erstellt",
+                Some("channel"),
+                Some(&format!("kanal-{nr}")),
+                serde_json::json!({ "nr": nr }),
+            )
+        })
+        .collect();
+    AuditLogRepository::log_events(&db, &eintraege)
+        .await
+        .unwrap();
+    AuditLogRepository::log_events(&db, &[]).await.unwrap();
+
+    let gespeichert = db
+        .list_events(AuditLogFilter {
+            action: Some("kanal.erstellt".into()),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    assert_eq!(gespeichert.len(), 3);
+    assert_eq!(gespeichert[0].target_id.as_deref(), Some("kanal-2"));
+    assert_eq!(gespeichert[0].details["nr"], 2);
+}
@@ -0,0 +1,317 @@
+//! Integration-Tests fuer ChannelTemplateRepository (In-Memory SQLite)
+
+use speakeasy_db::{
+    models::{
+        BerechtigungsWert, BerechtigungsZiel, KanalTyp, KanalbaumGrenzen, NeueKanalGruppe,
+        NeueKanalVorlage, NeuerKanal, TriState, VorlagenBerechtigung, VorlagenKnoten, VorlagenZiel,
+    },
+    ChannelGroupRepository, ChannelRepository, ChannelTemplateRepository, DbError,
+    PermissionRepository, SqliteDb,
+};
+
+async fn db() -> SqliteDb {
+    SqliteDb::in_memory()
+        .await
+        .expect("In-Memory DB konnte nicht erstellt werden")
+}
+
+fn knoten(name: &str, children: Vec<VorlagenKnoten>) -> VorlagenKnoten {
+    VorlagenKnoten {
+        name: name.into(),
+        channel_type: KanalTyp::Voice,
+        topic: None,
+        max_clients: 0,
+        codec_profile: None,
+        permissions: vec![],
+        children,
+    }
+}
+
+fn gruppen_recht(gruppe: &str) -> VorlagenBerechtigung {
+    VorlagenBerechtigung {
+        target: VorlagenZiel::ChannelGroup(gruppe.into()),
+        permission_key: "b_channel_join".into(),
+        wert: BerechtigungsWert::TriState(TriState::Grant),
+    }
+}
+
+async fn vorlage_anlegen(db: &SqliteDb, name: &str, root: VorlagenKnoten) -> uuid::Uuid {
+    ChannelTemplateRepository::create(
+        db,
+        NeueKanalVorlage {
+            name,
+            description: None,
+            root,
+            created_by: None,
+        },
+    )
+    .await
+    .unwrap()
+    .id
+}
+
+async fn kanal_anzahl(db: &SqliteDb) -> usize {
+    ChannelRepository::list(db).await.unwrap().len()
+}
+
+#[tokio::test]
+async fn mitgelieferte_vorlagen_vorhanden() {
+    let db = db().await;
+
+    let vorlagen = ChannelTemplateRepository::list(&db).await.unwrap();
+    let eingebaut: Vec<_> = vorlagen.iter().filter(|v| v.builtin).collect();
+    assert_eq!(eingebaut.len(), 2);
+    for v in eingebaut {
+        v.root
+            .pruefen()
+            .expect("mitgelieferte Vorlage muss gueltig sein");
+        assert!(v.root.anzahl_kanaele() > 1);
+    }
+}
+
+#[tokio::test]
+async fn mitgelieferte_vorlage_nicht_loeschbar() {
+    let db = db().await;
+
+    let vorlage = ChannelTemplateRepository::list(&db)
+        .await
+        .unwrap()
+        .into_iter()
+        .find(|v| v.builtin)
+        .unwrap();
+    let err = ChannelTemplateRepository::delete(&db, vorlage.id)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, DbError::UngueltigeDaten(_)));
+}
+
+#[tokio::test]
+async fn vorlage_erstellen_loeschen() {
+    let db = db().await;
+
+    let id = vorlage_anlegen(
+        &db,
+        "Turnier",
+        knoten("Turnier", vec![knoten("Lobby", vec![])]),
+    )
+    .await;
+    let geladen = ChannelTemplateRepository::get_by_id(&db, id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(geladen.root.children[0].name, "Lobby");
+    assert!(!geladen.builtin);
+
+    assert!(ChannelTemplateRepository::delete(&db, id).await.unwrap());
+    assert!(ChannelTemplateRepository::get_by_id(&db, id)
+        .await
+        .unwrap()
+        .is_none());
+}
+
+#[tokio::test]
+async fn ungueltige_definition_abgelehnt() {
+    let db = db().await;
+
+    let mut root = knoten("Kategorie", vec![knoten("", vec![])]);
+    let err = ChannelTemplateRepository::create(
+        &db,
+        NeueKanalVorlage {
+            name: "Kaputt",
+            description: None,
+            root: root.clone(),
+            created_by: None,
+        },
+    )
+    .await
+    .unwrap_err();
+    assert!(matches!(err, DbError::UngueltigeDaten(_)));
+
+    root.children[0].name = "Kind".into();
+    root.codec_profile = Some("unbekannt".into());
+    assert!(root.pruefen().is_err());
+}
+
+#[tokio::test]
+async fn baum_instanziieren() {
+    let db = db().await;
+
+    let eltern = ChannelRepository::create(
+        &db,
+        NeuerKanal {
+            name: "Spiele",
+            ..Default::default()
+        },
+    )
+    .await
+    .unwrap();
+
+    let mut afk = knoten("AFK", vec![]);
+    afk.codec_profile = Some("low_bandwidth".into());
+    afk.permissions.push(VorlagenBerechtigung {
+        target: VorlagenZiel::ChannelDefault,
+        permission_key: "b_client_poke".into(),
+        wert: BerechtigungsWert::TriState(TriState::Deny),
+    });
+    let root = knoten(
+        "Kategorie",
+        vec![knoten("Lobby", vec![]), knoten("Team 1", vec![]), afk],
+    );
+    let id = vorlage_anlegen(&db, "Spiel", root).await;
+
+    let kanaele = ChannelTemplateRepository::instantiate(
+        &db,
+        id,
+        Some(eltern.id),
+        Some("CS: "),
+        KanalbaumGrenzen::default(),
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(kanaele.len(), 4);
+    assert_eq!(kanaele[0].name, "CS: Kategorie");
+    assert_eq!(kanaele[0].parent_id, Some(eltern.id));
+
+    let kinder = ChannelRepository::get_children(&db, kanaele[0].id)
+        .await
+        .unwrap();
+    let namen: Vec<_> = kinder.iter().map(|k| k.name.as_str()).collect();
+    assert_eq!(namen, ["Lobby", "Team 1", "AFK"]);
+
+    let afk = &kinder[2];
+    assert_eq!(afk.codec_profile.as_deref(), Some("low_bandwidth"));
+    let rechte = db
+        .get_permissions(&BerechtigungsZiel::KanalDefault(afk.id), Some(afk.id))
+        .await
+        .unwrap();
+    assert_eq!(rechte.len(), 1);
+    assert_eq!(rechte[0].0, "b_client_poke");
+}
+
+#[tokio::test]
+async fn gruppen_zuweisung_wird_aufgeloest() {
+    let db = db().await;
+
+    let gruppe = ChannelGroupRepository::create(&db, NeueKanalGruppe { name: "Teamleiter" })
+        .await
+        .unwrap();
+    let mut root = knoten("Team", vec![]);
+    root.permissions.push(gruppen_recht("Teamleiter"));
+    let id = vorlage_anlegen(&db, "Team", root).await;
+
+    let kanaele =
+        ChannelTemplateRepository::instantiate(&db, id, None, None, KanalbaumGrenzen::default())
+            .await
+            .unwrap();
+
+    let rechte = db
+        .get_permissions(
+            &BerechtigungsZiel::KanalGruppe(gruppe.id),
+            Some(kanaele[0].id),
+        )
+        .await
+        .unwrap();
+    assert_eq!(rechte.len(), 1);
+}
+
+#[tokio::test]
+async fn fehler_in_einem_kanal_rollt_gesamten_baum_zurueck() {
+    let db = db().await;
+
+    // Der letzte Kanal referenziert eine nicht existierende Gruppe – zu dem
+    // Zeitpunkt sind Wurzel und Geschwister bereits eingefuegt.
+    let mut kaputt = knoten("Team 2", vec![]);
+    kaputt.permissions.push(gruppen_recht("GibtEsNicht"));
+    let root = knoten(
+        "Kategorie",
+        vec![knoten("Lobby", vec![knoten("Warteraum", vec![])]), kaputt],
+    );
+    let id = vorlage_anlegen(&db, "Defekt", root).await;
+
+    let vorher = kanal_anzahl(&db).await;
+    let err =
+        ChannelTemplateRepository::instantiate(&db, id, None, None, KanalbaumGrenzen::default())
+            .await
+            .unwrap_err();
+    assert!(matches!(err, DbError::UngueltigeDaten(_)));
+
+    assert_eq!(
+        kanal_anzahl(&db).await,
+        vorher,
+        "kein Kanal darf uebrig bleiben"
+    );
+    assert!(ChannelRepository::list(&db)
+        .await
+        .unwrap()
+        .iter()
+        .all(|k| k.name != "Kategorie" && k.name != "Lobby"));
+}
+
+#[tokio::test]
+async fn kanal_limit_wird_eingehalten() {
+    let db = db().await;
+
+    let id = vorlage_anlegen(
+        &db,
+        "Drei",
+        knoten("A", vec![knoten("B", vec![]), knoten("C", vec![])]),
+    )
+    .await;
+    let vorher = kanal_anzahl(&db).await as u32;
+
+    let grenzen = KanalbaumGrenzen {
+        max_kanaele: vorher + 2,
+        max_tiefe: 0,
+    };
+    let err = ChannelTemplateRepository::instantiate(&db, id, None, None, grenzen)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, DbError::UngueltigeDaten(_)));
+    assert_eq!(kanal_anzahl(&db).await as u32, vorher);
+
+    let grenzen = KanalbaumGrenzen {
+        max_kanaele: vorher + 3,
+        max_tiefe: 0,
+    };
+    ChannelTemplateRepository::instantiate(&db, id, None, None, grenzen)
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn verschachtelungstiefe_wird_eingehalten() {
+    let db = db().await;
+
+    let ebene1 = ChannelRepository::create(
+        &db,
+        NeuerKanal {
+            name: "Ebene 1",
+            ..Default::default()
+        },
+    )
+    .await
+    .unwrap();
+    let id = vorlage_anlegen(
+        &db,
+        "Tief",
+        knoten("A", vec![knoten("B", vec![knoten("C", vec![])])]),
+    )
+    .await;
+
+    let grenzen = KanalbaumGrenzen {
+        max_kanaele: 0,
+        max_tiefe: 3,
+    };
+    let vorher = kanal_anzahl(&db).await;
+    let err = ChannelTemplateRepository::instantiate(&db, id, Some(ebene1.id), None, grenzen)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, DbError::UngueltigeDaten(_)));
+    assert_eq!(kanal_anzahl(&db).await, vorher);
+
+    // Auf Root-Ebene passt der Baum genau
+    ChannelTemplateRepository::instantiate(&db, id, None, None, grenzen)
+        .await
+        .unwrap();
+}
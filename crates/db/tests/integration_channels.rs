@@ -1,7 +1,7 @@
 //! Integration-Tests fuer ChannelRepository (In-Memory SQLite)
 
 use speakeasy_db::{
-    models::{KanalTyp, KanalUpdate, NeuerKanal},
+    models::{KanalFilter, KanalTyp, KanalUpdate, NeuerKanal},
     ChannelRepository, SqliteDb,
 };
 
@@ -166,6 +166,100 @@ async fn kanal_aktualisieren() {
     assert_eq!(aktualisiert.topic.as_deref(), Some("Neues Thema"));
 }
 
+#[tokio::test]
+async fn langsam_modus_setzen_und_aufheben() {
+    let db = db().await;
+
+    let kanal = ChannelRepository::create(
+        &db,
+        NeuerKanal {
+            name: "Plaudern",
+            ..Default::default()
+        },
+    )
+    .await
+    .unwrap();
+    assert_eq!(kanal.slow_mode_secs, 0);
+
+    let gesetzt = ChannelRepository::update(
+        &db,
+        kanal.id,
+        KanalUpdate {
+            slow_mode_secs: Some(30),
+            ..Default::default()
+        },
+    )
+    .await
+    .unwrap();
+    assert_eq!(gesetzt.slow_mode_secs, 30);
+    assert_eq!(gesetzt.name, "Plaudern");
+
+    let aufgehoben = ChannelRepository::update(
+        &db,
+        kanal.id,
+        KanalUpdate {
+            slow_mode_secs: Some(0),
+            ..Default::default()
+        },
+    )
+    .await
+    .unwrap();
+    assert_eq!(aufgehoben.slow_mode_secs, 0);
+}
+
+#[tokio::test]
+async fn bearbeitungsfrist_setzen_und_zuruecksetzen() {
+    let db = db().await;
+
+    let kanal = ChannelRepository::create(
+        &db,
+        NeuerKanal {
+            name: "Ankuendigungen",
+            ..Default::default()
+        },
+    )
+    .await
+    .unwrap();
+    assert_eq!(kanal.edit_window_secs, None);
+
+    let gesetzt = ChannelRepository::update(
+        &db,
+        kanal.id,
+        KanalUpdate {
+            edit_window_secs: Some(Some(900)),
+            ..Default::default()
+        },
+    )
+    .await
+    .unwrap();
+    assert_eq!(gesetzt.edit_window_secs, Some(900));
+
+    // Andere Aenderungen lassen die Frist stehen
+    let umbenannt = ChannelRepository::update(
+        &db,
+        kanal.id,
+        KanalUpdate {
+            name: Some("News".into()),
+            ..Default::default()
+        },
+    )
+    .await
+    .unwrap();
+    assert_eq!(umbenannt.edit_window_secs, Some(900));
+
+    let zurueckgesetzt = ChannelRepository::update(
+        &db,
+        kanal.id,
+        KanalUpdate {
+            edit_window_secs: Some(None),
+            ..Default::default()
+        },
+    )
+    .await
+    .unwrap();
+    assert_eq!(zurueckgesetzt.edit_window_secs, None);
+}
+
 #[tokio::test]
 async fn kanal_loeschen() {
     let db = db().await;
@@ -233,3 +327,129 @@ async fn text_kanal_typ() {
         .unwrap();
     assert_eq!(geladen.channel_type, KanalTyp::Text);
 }
+
+#[tokio::test]
+async fn beitritt_mit_freigabe_umschalten() {
+    let db = db().await;
+
+    let kanal = ChannelRepository::create(
+        &db,
+        NeuerKanal {
+            name: "Hinterzimmer",
+            ..Default::default()
+        },
+    )
+    .await
+    .unwrap();
+    assert!(!kanal.join_by_approval);
+
+    let gesetzt = ChannelRepository::update(
+        &db,
+        kanal.id,
+        KanalUpdate {
+            join_by_approval: Some(true),
+            ..Default::default()
+        },
+    )
+    .await
+    .unwrap();
+    assert!(gesetzt.join_by_approval);
+    assert!(ChannelRepository::get_by_id(&db, kanal.id)
+        .await
+        .unwrap()
+        .unwrap()
+        .join_by_approval);
+}
+
+async fn kanaele_anlegen(db: &SqliteDb, namen: &[&str]) {
+    for (i, name) in namen.iter().enumerate() {
+        ChannelRepository::create(
+            db,
+            NeuerKanal {
+                name,
+                sort_order: i as i64,
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+    }
+}
+
+fn kanal_namen(kanaele: &[speakeasy_db::models::KanalRecord]) -> Vec<&str> {
+    kanaele.iter().map(|k| k.name.as_str()).collect()
+}
+
+#[tokio::test]
+async fn kanaele_seitenweise_auflisten() {
+    let db = db().await;
+    kanaele_anlegen(&db, &["K1", "K2", "K3", "K4", "K5"]).await;
+    let gesamt = ChannelRepository::count_filtered(&db, KanalFilter::default())
+        .await
+        .unwrap();
+    let alle = ChannelRepository::list(&db).await.unwrap();
+    assert_eq!(gesamt, alle.len() as i64);
+
+    let seite = |offset| KanalFilter {
+        limit: Some(2),
+        offset: Some(offset),
+        ..Default::default()
+    };
+    let mut gesehen = Vec::new();
+    let mut offset = 0;
+    loop {
+        let teil = ChannelRepository::list_filtered(&db, seite(offset))
+            .await
+            .unwrap();
+        assert!(teil.len() <= 2);
+        if teil.is_empty() {
+            break;
+        }
+        gesehen.extend(teil.into_iter().map(|k| k.id));
+        offset += 2;
+    }
+    // Alle Seiten zusammen ergeben genau die ungefilterte Liste
+    assert_eq!(gesehen, alle.iter().map(|k| k.id).collect::<Vec<_>>());
+
+    // Jenseits des Endes: leere Seite, Gesamtzahl unveraendert
+    let leer = ChannelRepository::list_filtered(&db, seite(gesamt + 10))
+        .await
+        .unwrap();
+    assert!(leer.is_empty());
+}
+
+#[tokio::test]
+async fn kanaele_nach_namen_filtern() {
+    let db = db().await;
+    kanaele_anlegen(&db, &["Lobby-A", "Musik", "lobby-b", "LOBBY-C", "100%_ok"]).await;
+
+    let filter = KanalFilter {
+        name_contains: Some("lobby".into()),
+        ..Default::default()
+    };
+    assert_eq!(
+        ChannelRepository::count_filtered(&db, filter.clone())
+            .await
+            .unwrap(),
+        3
+    );
+    let zweite_seite = ChannelRepository::list_filtered(
+        &db,
+        KanalFilter {
+            limit: Some(2),
+            offset: Some(2),
+            ..filter
+        },
+    )
+    .await
+    .unwrap();
+    assert_eq!(kanal_namen(&zweite_seite), ["LOBBY-C"]);
+
+    // Platzhalter werden woertlich gesucht
+    let filter = KanalFilter {
+        name_contains: Some("%_".into()),
+        ..Default::default()
+    };
+    let treffer = ChannelRepository::list_filtered(&db, filter).await.unwrap();
+    assert_eq!(kanal_namen(&treffer), ["100%_ok"]);
+}
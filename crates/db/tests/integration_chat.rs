@@ -0,0 +1,80 @@
+//! Integration-Tests fuer ChatMessageRepository (In-Memory SQLite)
+
+use speakeasy_db::{
+    models::{
+        KanalTyp, NachrichtenSuche, NachrichtenTyp, NeueNachricht, NeuerBenutzer, NeuerKanal,
+    },
+    ChannelRepository, ChatMessageRepository, SqliteDb, UserRepository,
+};
+
+async fn db() -> SqliteDb {
+    SqliteDb::in_memory()
+        .await
+        .expect("In-Memory DB konnte nicht erstellt werden")
+}
+
+#[tokio::test]
+async fn suche_blaettert_ueber_zeitgleiche_treffer() {
+    let db = db().await;
+    let user = UserRepository::create(
+        &db,
+        NeuerBenutzer {
+            username: "erika",
+            password_hash: "hash",
+        },
+    )
+    .await
+    .unwrap();
+    let kanal = ChannelRepository::create(
+        &db,
+        NeuerKanal {
+            name: "Lobby",
+            channel_type: KanalTyp::Text,
+            ..Default::default()
+        },
+    )
+    .await
+    .unwrap();
+
+    for text in ["Treffer eins", "Treffer zwei", "Treffer drei"] {
+        ChatMessageRepository::create(
+            &db,
+            NeueNachricht {
+                channel_id: kanal.id,
+                sender_id: user.id,
+                content: text,
+                message_type: NachrichtenTyp::Text,
+                reply_to: None,
+            },
+        )
+        .await
+        .unwrap();
+    }
+    // Alle Treffer in derselben Sekunde, die Seitengrenze faellt mitten hinein
+    sqlx::query("UPDATE chat_messages SET created_at = '2026-01-01T12:00:00Z'")
+        .execute(db.pool())
+        .await
+        .unwrap();
+
+    let mut inhalte = Vec::new();
+    let mut cursor = None;
+    loop {
+        let seite = ChatMessageRepository::search(
+            &db,
+            NachrichtenSuche {
+                channel_id: kanal.id,
+                query: "Treffer",
+                before: cursor.map(|(zeit, _)| zeit),
+                before_id: cursor.map(|(_, id)| id),
+                limit: 2,
+                offset: 0,
+            },
+        )
+        .await
+        .unwrap();
+        let Some(letzter) = seite.last() else { break };
+        cursor = Some((letzter.created_at, letzter.id));
+        inhalte.extend(seite.iter().map(|n| n.content.clone()));
+    }
+    assert_eq!(inhalte, ["Treffer drei", "Treffer zwei", "Treffer eins"]);
+}
@@ -0,0 +1,207 @@
+//! Integration-Tests fuer das Datei-Zugriffsprotokoll (In-Memory SQLite)
+
+use speakeasy_db::{
+    models::{
+        DateiZugriffFilter, KanalTyp, NeueDatei, NeuerBenutzer, NeuerDateiZugriff, NeuerKanal,
+    },
+    ChannelRepository, FileRepository, SqliteDb, UserRepository,
+};
+use uuid::Uuid;
+
+async fn db() -> SqliteDb {
+    SqliteDb::in_memory()
+        .await
+        .expect("In-Memory DB konnte nicht erstellt werden")
+}
+
+async fn datei_anlegen(db: &SqliteDb) -> (Uuid, Uuid) {
+    let user = UserRepository::create(
+        db,
+        NeuerBenutzer {
+            username: "leser",
+            password_hash: "hash",
+        },
+    )
+    .await
+    .unwrap();
+
+    let kanal = ChannelRepository::create(
+        db,
+        NeuerKanal {
+            name: "dateien",
+            channel_type: KanalTyp::Text,
+            ..Default::default()
+        },
+    )
+    .await
+    .unwrap();
+
+    let datei = FileRepository::create(
+        db,
+        NeueDatei {
+            channel_id: kanal.id,
+            uploader_id: user.id,
+            filename: "bericht.pdf",
+            mime_type: "application/pdf",
+            size_bytes: 1024,
+            storage_path: "kanal/bericht.pdf",
+            checksum: "abc",
+        },
+    )
+    .await
+    .unwrap();
+
+    (datei.id, user.id)
+}
+
+#[tokio::test]
+async fn zugriff_protokollieren_und_filtern() {
+    let db = db().await;
+    let (file_id, user_id) = datei_anlegen(&db).await;
+
+    for completed in [true, false, true] {
+        db.log_access(NeuerDateiZugriff {
+            file_id,
+            user_id: Some(user_id),
+            bytes_served: 1024,
+            completed,
+        })
+        .await
+        .unwrap();
+    }
+    db.log_access(NeuerDateiZugriff {
+        file_id,
+        user_id: None,
+        bytes_served: 512,
+        completed: false,
+    })
+    .await
+    .unwrap();
+
+    let alle = db
+        .count_access(DateiZugriffFilter {
+            file_id: Some(file_id),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    assert_eq!(alle, 4);
+
+    let vom_user = db
+        .list_access(DateiZugriffFilter {
+            user_id: Some(user_id),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    assert_eq!(vom_user.len(), 3);
+    assert!(vom_user.iter().all(|z| z.user_id == Some(user_id)));
+}
+
+#[tokio::test]
+async fn zugriffe_paginieren() {
+    let db = db().await;
+    let (file_id, user_id) = datei_anlegen(&db).await;
+
+    for _ in 0..5 {
+        db.log_access(NeuerDateiZugriff {
+            file_id,
+            user_id: Some(user_id),
+            bytes_served: 1,
+            completed: true,
+        })
+        .await
+        .unwrap();
+    }
+
+    let seite = db
+        .list_access(DateiZugriffFilter {
+            file_id: Some(file_id),
+            limit: Some(2),
+            offset: Some(4),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    assert_eq!(seite.len(), 1);
+
+    // count_access ignoriert die Paginierung
+    let gesamt = db
+        .count_access(DateiZugriffFilter {
+            file_id: Some(file_id),
+            limit: Some(2),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    assert_eq!(gesamt, 5);
+}
+
+#[tokio::test]
+async fn alte_zugriffe_bereinigen() {
+    let db = db().await;
+    let (file_id, _) = datei_anlegen(&db).await;
+
+    db.log_access(NeuerDateiZugriff {
+        file_id,
+        user_id: None,
+        bytes_served: 10,
+        completed: true,
+    })
+    .await
+    .unwrap();
+
+    let zukunft = chrono::Utc::now() + chrono::Duration::hours(1);
+    let geloescht = db.purge_access_before(zukunft).await.unwrap();
+    assert_eq!(geloescht, 1);
+
+    let rest = db
+        .count_access(DateiZugriffFilter::default())
+        .await
+        .unwrap();
+    assert_eq!(rest, 0);
+}
+
+#[tokio::test]
+async fn zugriffe_je_datei_zaehlen() {
+    let db = db().await;
+    let (mit_zugriffen, user_id) = datei_anlegen(&db).await;
+    let kanal_id = FileRepository::get_by_id(&db, mit_zugriffen)
+        .await
+        .unwrap()
+        .unwrap()
+        .channel_id;
+    let ohne_zugriffe = FileRepository::create(
+        &db,
+        NeueDatei {
+            channel_id: kanal_id,
+            uploader_id: user_id,
+            filename: "notiz.txt",
+            mime_type: "text/plain",
+            size_bytes: 10,
+            storage_path: "kanal/notiz.txt",
+            checksum: "def",
+        },
+    )
+    .await
+    .unwrap()
+    .id;
+
+    for _ in 0..3 {
+        db.log_access(NeuerDateiZugriff {
+            file_id: mit_zugriffen,
+            user_id: Some(user_id),
+            bytes_served: 1,
+            completed: true,
+        })
+        .await
+        .unwrap();
+    }
+
+    let zaehlung = db
+        .count_access_by_file(&[mit_zugriffen, ohne_zugriffe])
+        .await
+        .unwrap();
+    assert_eq!(zaehlung, vec![(mit_zugriffen, 3)]);
+    assert!(db.count_access_by_file(&[]).await.unwrap().is_empty());
+}
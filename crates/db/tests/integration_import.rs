@@ -0,0 +1,206 @@
+//! Integration-Tests fuer ImportRepository (In-Memory SQLite)
+
+use speakeasy_db::{
+    models::{
+        BerechtigungsWert, BerechtigungsZiel, ImportBenutzer, ImportServerGruppe, KanalTyp,
+        KanalbaumGrenzen, NeueServerGruppe, NeuerBenutzer, TriState, VorlagenKnoten,
+    },
+    ChannelRepository, DbError, ImportRepository, PermissionRepository, ServerGroupRepository,
+    SqliteDb, UserRepository,
+};
+
+async fn db() -> SqliteDb {
+    SqliteDb::in_memory()
+        .await
+        .expect("In-Memory DB konnte nicht erstellt werden")
+}
+
+fn knoten(name: &str, children: Vec<VorlagenKnoten>) -> VorlagenKnoten {
+    VorlagenKnoten {
+        name: name.into(),
+        channel_type: KanalTyp::Voice,
+        topic: None,
+        max_clients: 0,
+        codec_profile: None,
+        permissions: vec![],
+        children,
+    }
+}
+
+fn gruppe(name: &str) -> ImportServerGruppe {
+    ImportServerGruppe {
+        name: name.into(),
+        priority: 10,
+        berechtigungen: vec![
+            (
+                "b_client_kick_server".into(),
+                BerechtigungsWert::TriState(TriState::Grant),
+            ),
+            (
+                "i_client_move_power".into(),
+                BerechtigungsWert::IntLimit(50),
+            ),
+        ],
+    }
+}
+
+#[tokio::test]
+async fn kanalbaeume_werden_in_pre_order_angelegt() {
+    let db = db().await;
+    let vorher = ChannelRepository::list(&db).await.unwrap().len();
+
+    let kanaele = db
+        .kanaele_importieren(
+            &[
+                knoten("Lobby", vec![knoten("Ecke", vec![])]),
+                knoten("Spiele", vec![]),
+            ],
+            KanalbaumGrenzen::default(),
+        )
+        .await
+        .unwrap();
+
+    let namen: Vec<&str> = kanaele.iter().map(|k| k.name.as_str()).collect();
+    assert_eq!(namen, ["Lobby", "Ecke", "Spiele"]);
+    assert_eq!(kanaele[1].parent_id, Some(kanaele[0].id));
+    assert!(kanaele[2].parent_id.is_none());
+    assert!(kanaele[2].sort_order > kanaele[0].sort_order);
+    assert_eq!(
+        ChannelRepository::list(&db).await.unwrap().len(),
+        vorher + 3
+    );
+}
+
+#[tokio::test]
+async fn kanal_limit_verwirft_den_ganzen_abschnitt() {
+    let db = db().await;
+    let vorher = ChannelRepository::list(&db).await.unwrap().len();
+
+    let err = db
+        .kanaele_importieren(
+            &[knoten("A", vec![]), knoten("B", vec![])],
+            KanalbaumGrenzen {
+                max_kanaele: vorher as u32 + 1,
+                max_tiefe: 0,
+            },
+        )
+        .await
+        .unwrap_err();
+    assert!(matches!(err, DbError::UngueltigeDaten(_)));
+    assert_eq!(ChannelRepository::list(&db).await.unwrap().len(), vorher);
+}
+
+#[tokio::test]
+async fn gruppen_mit_berechtigungen() {
+    let db = db().await;
+
+    let gruppen = db
+        .server_gruppen_importieren(&[gruppe("Moderatoren")])
+        .await
+        .unwrap();
+    assert_eq!(gruppen.len(), 1);
+
+    let mut regeln = db
+        .get_permissions(&BerechtigungsZiel::ServerGruppe(gruppen[0].id), None)
+        .await
+        .unwrap();
+    regeln.sort_by(|a, b| a.0.cmp(&b.0));
+    assert_eq!(
+        regeln,
+        vec![
+            (
+                "b_client_kick_server".to_string(),
+                BerechtigungsWert::TriState(TriState::Grant)
+            ),
+            (
+                "i_client_move_power".to_string(),
+                BerechtigungsWert::IntLimit(50)
+            ),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn namenskonflikt_rollt_alle_gruppen_zurueck() {
+    let db = db().await;
+    ServerGroupRepository::create(
+        &db,
+        NeueServerGruppe {
+            name: "Admins",
+            priority: 0,
+            is_default: false,
+        },
+    )
+    .await
+    .unwrap();
+    let vorher = ServerGroupRepository::list(&db).await.unwrap().len();
+
+    let err = db
+        .server_gruppen_importieren(&[gruppe("Neu"), gruppe("Admins")])
+        .await
+        .unwrap_err();
+    assert!(matches!(err, DbError::Eindeutigkeit(_)));
+    assert_eq!(
+        ServerGroupRepository::list(&db).await.unwrap().len(),
+        vorher
+    );
+}
+
+#[tokio::test]
+async fn benutzer_mit_gruppen_und_passwortwechsel() {
+    let db = db().await;
+    let gruppen = db
+        .server_gruppen_importieren(&[gruppe("Stammgaeste")])
+        .await
+        .unwrap();
+
+    let benutzer = db
+        .benutzer_importieren(&[ImportBenutzer {
+            username: "alice".into(),
+            password_hash: "hash".into(),
+            gruppen: vec![gruppen[0].id],
+        }])
+        .await
+        .unwrap();
+
+    let geladen = UserRepository::get_by_name(&db, "alice")
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(geladen.id, benutzer[0].id);
+    assert!(!geladen.password_changed);
+    let mitglied_in = db.list_for_user(geladen.id).await.unwrap();
+    assert_eq!(mitglied_in.len(), 1);
+    assert_eq!(mitglied_in[0].name, "Stammgaeste");
+
+    // Konflikt mit bestehendem Namen: keiner des Abschnitts wird angelegt
+    UserRepository::create(
+        &db,
+        NeuerBenutzer {
+            username: "bob",
+            password_hash: "hash",
+        },
+    )
+    .await
+    .unwrap();
+    let err = db
+        .benutzer_importieren(&[
+            ImportBenutzer {
+                username: "carol".into(),
+                password_hash: "hash".into(),
+                gruppen: vec![],
+            },
+            ImportBenutzer {
+                username: "bob".into(),
+                password_hash: "hash".into(),
+                gruppen: vec![],
+            },
+        ])
+        .await
+        .unwrap_err();
+    assert!(matches!(err, DbError::Eindeutigkeit(_)));
+    assert!(UserRepository::get_by_name(&db, "carol")
+        .await
+        .unwrap()
+        .is_none());
+}
@@ -150,6 +150,48 @@ async fn einladung_erschoepft() {
     assert!(matches!(err.unwrap_err(), DbError::EinladungErschoepft));
 }
 
+#[tokio::test]
+async fn einladung_nutzung_zurueckgeben() {
+    let db = db().await;
+    let user_id = erstelle_user(&db, "einlader8").await;
+
+    InviteRepository::create(
+        &db,
+        NeueEinladung {
+            code: "RELEASE1",
+            channel_id: None,
+            assigned_group_id: None,
+            max_uses: 1,
+            expires_at: None,
+            created_by: user_id,
+        },
+    )
+    .await
+    .unwrap();
+
+    InviteRepository::use_invite(&db, "RELEASE1").await.unwrap();
+    assert!(InviteRepository::release_invite(&db, "RELEASE1")
+        .await
+        .unwrap());
+    // Die zurueckgegebene Nutzung ist wieder einloesbar
+    let einladung = InviteRepository::use_invite(&db, "RELEASE1")
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(einladung.used_count, 1);
+
+    // Nie unter 0, unbekannte Codes melden false
+    InviteRepository::release_invite(&db, "RELEASE1")
+        .await
+        .unwrap();
+    assert!(!InviteRepository::release_invite(&db, "RELEASE1")
+        .await
+        .unwrap());
+    assert!(!InviteRepository::release_invite(&db, "GIBTSNICHT")
+        .await
+        .unwrap());
+}
+
 #[tokio::test]
 async fn einladung_abgelaufen() {
     let db = db().await;
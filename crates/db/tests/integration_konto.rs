@@ -0,0 +1,442 @@
+//! Integration-Tests fuer KontoRepository (In-Memory SQLite)
+
+use chrono::{Duration, Utc};
+use speakeasy_db::{
+    models::{
+        AuditLogFilter, BerechtigungsWert, BerechtigungsZiel, KanalTyp, KontoLoeschAuftrag,
+        NachrichtenRichtlinie, NachrichtenTyp, NeueDatei, NeueInhaltsBereinigung, NeueNachricht,
+        NeueServerGruppe, NeuerAuditEintrag, NeuerBenutzer, NeuerKanal, NeuerKontoExport, TriState,
+        GELOESCHTER_BENUTZER,
+    },
+    AuditLogRepository, ChannelRepository, ChatMessageRepository, DbError, FileRepository,
+    KontoRepository, PermissionRepository, ServerGroupRepository, SqliteDb, UserRepository,
+};
+use uuid::Uuid;
+
+async fn db() -> SqliteDb {
+    SqliteDb::in_memory()
+        .await
+        .expect("In-Memory DB konnte nicht erstellt werden")
+}
+
+/// Legt einen Benutzer mit Nachrichten, Datei, Gruppe, Berechtigung und
+/// Audit-Eintrag an; gibt (Benutzer, Kanal, Nachrichten-IDs) zurueck
+async fn konto_anlegen(db: &SqliteDb) -> (Uuid, Uuid, Vec<Uuid>) {
+    let user = UserRepository::create(
+        db,
+        NeuerBenutzer {
+            username: "erika",
+            password_hash: "hash",
+        },
+    )
+    .await
+    .unwrap();
+    let kanal = ChannelRepository::create(
+        db,
+        NeuerKanal {
+            name: "Lobby",
+            channel_type: KanalTyp::Text,
+            ..Default::default()
+        },
+    )
+    .await
+    .unwrap();
+
+    let mut nachrichten = Vec::new();
+    for text in ["Hallo", "Wie gehts?"] {
+        let nachricht = ChatMessageRepository::create(
+            db,
+            NeueNachricht {
+                channel_id: kanal.id,
+                sender_id: user.id,
+                content: text,
+                message_type: NachrichtenTyp::Text,
+                reply_to: None,
+            },
+        )
+        .await
+        .unwrap();
+        nachrichten.push(nachricht.id);
+    }
+
+    FileRepository::create(
+        db,
+        NeueDatei {
+            channel_id: kanal.id,
+            uploader_id: user.id,
+            filename: "urlaub.jpg",
+            mime_type: "image/jpeg",
+            size_bytes: 300,
+            storage_path: "lobby/urlaub.jpg",
+            checksum: "abc",
+        },
+    )
+    .await
+    .unwrap();
+    db.increment_usage("default", 300).await.unwrap();
+
+    let gruppe = ServerGroupRepository::create(
+        db,
+        NeueServerGruppe {
+            name: "Stammgast",
+            priority: 5,
+            is_default: false,
+        },
+    )
+    .await
+    .unwrap();
+    db.add_member(gruppe.id, user.id).await.unwrap();
+    db.set_permission(
+        &BerechtigungsZiel::Benutzer(user.id),
+        "b_channel_create",
+        BerechtigungsWert::TriState(TriState::Grant),
+        None,
+    )
+    .await
+    .unwrap();
+
+    db.log_events(&[NeuerAuditEintrag::neu(
+        Some(user.id),
+        "kanal.erstellt",
+        Some("channel"),
+        Some(&kanal.id.to_string()),
+        serde_json::json!({}),
+    )])
+    .await
+    .unwrap();
+
+    (user.id, kanal.id, nachrichten)
+}
+
+async fn fremdschluessel_pruefen(db: &SqliteDb) {
+    let verletzungen = sqlx::query("PRAGMA foreign_key_check")
+        .fetch_all(db.pool())
+        .await
+        .unwrap();
+    assert!(
+        verletzungen.is_empty(),
+        "{} Verletzungen",
+        verletzungen.len()
+    );
+}
+
+#[tokio::test]
+async fn export_enthaelt_nachrichten_dateien_und_audit() {
+    let db = db().await;
+    let (user_id, kanal_id, nachrichten) = konto_anlegen(&db).await;
+
+    let daten = db.daten_sammeln(user_id).await.unwrap().unwrap();
+    assert_eq!(daten.profil.username, "erika");
+    assert_eq!(daten.profil.server_gruppen, vec!["Stammgast".to_string()]);
+
+    let ids: Vec<Uuid> = daten.nachrichten.iter().map(|n| n.id).collect();
+    assert_eq!(ids, nachrichten);
+    assert_eq!(daten.nachrichten[0].content, "Hallo");
+    assert!(daten
+        .nachrichten
+        .iter()
+        .all(|n| n.kanal_name == "Lobby" && n.channel_id == kanal_id));
+
+    assert_eq!(daten.dateien.len(), 1);
+    assert_eq!(daten.dateien[0].filename, "urlaub.jpg");
+    assert_eq!(daten.dateien[0].kanal_name, "Lobby");
+    assert_eq!(daten.audit.len(), 1);
+    assert_eq!(daten.audit[0].action, "kanal.erstellt");
+
+    assert!(db.daten_sammeln(Uuid::new_v4()).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn loeschen_anonymisiert_und_haelt_fremdschluessel_ein() {
+    let db = db().await;
+    let (user_id, kanal_id, nachrichten) = konto_anlegen(&db).await;
+
+    let ergebnis = db
+        .konto_loeschen(KontoLoeschAuftrag {
+            user_id,
+            richtlinie: NachrichtenRichtlinie::Behalten,
+            admin_id: None,
+            kontingent_gruppe: "default",
+        })
+        .await
+        .unwrap();
+    assert_eq!(ergebnis.nachrichten, 2);
+    assert_eq!(ergebnis.dateien.len(), 1);
+    assert_eq!(ergebnis.berechtigungen, 2);
+
+    fremdschluessel_pruefen(&db).await;
+    assert!(UserRepository::get_by_id(&db, user_id)
+        .await
+        .unwrap()
+        .is_none());
+
+    // Nachrichten bleiben mit Inhalt, aber ohne Absender erhalten
+    for id in nachrichten {
+        let nachricht = ChatMessageRepository::get_by_id(&db, id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(nachricht.sender_id, GELOESCHTER_BENUTZER);
+        assert!(!nachricht.content.is_empty());
+    }
+    assert!(db.list_by_channel(kanal_id).await.unwrap().is_empty());
+    assert_eq!(db.get_quota("default").await.unwrap().current_usage, 0);
+    assert!(db
+        .get_permissions(&BerechtigungsZiel::Benutzer(user_id), None)
+        .await
+        .unwrap()
+        .is_empty());
+
+    // Der Audit-Eintrag nennt weder ID noch Namen
+    let eintraege = db
+        .list_events(AuditLogFilter {
+            action: Some("konto.geloescht".into()),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    assert_eq!(eintraege.len(), 1);
+    let eintrag = serde_json::to_string(&eintraege[0]).unwrap();
+    assert!(!eintrag.contains(&user_id.to_string()));
+    assert!(!eintrag.contains("erika"));
+
+    // Der Platzhalter taucht in keiner Benutzerliste auf
+    assert!(UserRepository::list(&db, false).await.unwrap().is_empty());
+    assert!(matches!(
+        db.konto_loeschen(KontoLoeschAuftrag {
+            user_id,
+            richtlinie: NachrichtenRichtlinie::Behalten,
+            admin_id: None,
+            kontingent_gruppe: "default",
+        })
+        .await,
+        Err(DbError::NichtGefunden(_))
+    ));
+}
+
+#[tokio::test]
+async fn loeschen_mit_leeren_entfernt_inhalte() {
+    let db = db().await;
+    let (user_id, _, nachrichten) = konto_anlegen(&db).await;
+    let admin = UserRepository::create(
+        &db,
+        NeuerBenutzer {
+            username: "admin",
+            password_hash: "hash",
+        },
+    )
+    .await
+    .unwrap();
+
+    db.konto_loeschen(KontoLoeschAuftrag {
+        user_id,
+        richtlinie: NachrichtenRichtlinie::Leeren,
+        admin_id: Some(admin.id),
+        kontingent_gruppe: "default",
+    })
+    .await
+    .unwrap();
+    fremdschluessel_pruefen(&db).await;
+
+    let nachricht = ChatMessageRepository::get_by_id(&db, nachrichten[0])
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(nachricht.content.is_empty());
+    assert!(nachricht.deleted_at.is_some());
+
+    let daten = db.daten_sammeln(admin.id).await.unwrap().unwrap();
+    assert_eq!(daten.audit.len(), 1);
+    assert_eq!(daten.audit[0].details["ausloeser"], "admin");
+
+    assert!(matches!(
+        db.konto_loeschen(KontoLoeschAuftrag {
+            user_id: GELOESCHTER_BENUTZER,
+            richtlinie: NachrichtenRichtlinie::Behalten,
+            admin_id: Some(admin.id),
+            kontingent_gruppe: "default",
+        })
+        .await,
+        Err(DbError::UngueltigeDaten(_))
+    ));
+}
+
+#[tokio::test]
+async fn bereinigung_in_stapeln_bis_zur_fenstergrenze() {
+    let db = db().await;
+    let (user_id, kanal_id, nachrichten) = konto_anlegen(&db).await;
+    let zweiter = ChannelRepository::create(
+        &db,
+        NeuerKanal {
+            name: "Spam",
+            channel_type: KanalTyp::Text,
+            ..Default::default()
+        },
+    )
+    .await
+    .unwrap();
+    let spam = ChatMessageRepository::create(
+        &db,
+        NeueNachricht {
+            channel_id: zweiter.id,
+            sender_id: user_id,
+            content: "Kauft jetzt!",
+            message_type: NachrichtenTyp::Text,
+            reply_to: None,
+        },
+    )
+    .await
+    .unwrap();
+    // Die erste Nachricht liegt vor dem Fenster
+    sqlx::query("UPDATE chat_messages SET created_at = ? WHERE id = ?")
+        .bind(
+            (Utc::now() - Duration::hours(2))
+                .format("%Y-%m-%dT%H:%M:%SZ")
+                .to_string(),
+        )
+        .bind(nachrichten[0].to_string())
+        .execute(db.pool())
+        .await
+        .unwrap();
+
+    let auftrag = db
+        .bereinigung_anlegen(NeueInhaltsBereinigung {
+            user_id,
+            seit: Utc::now() - Duration::hours(1),
+            channel_id: None,
+            angefordert_von: None,
+        })
+        .await
+        .unwrap();
+
+    let mut stapel_anzahl = 0;
+    let mut geloescht = Vec::new();
+    loop {
+        let stapel = db
+            .bereinigung_stapel(auftrag.id, 1, "default")
+            .await
+            .unwrap();
+        if stapel.ist_leer() {
+            break;
+        }
+        stapel_anzahl += 1;
+        geloescht.extend(stapel.nachrichten.iter().map(|n| n.id));
+    }
+    assert_eq!(stapel_anzahl, 2);
+    geloescht.sort();
+    let mut erwartet = vec![nachrichten[1], spam.id];
+    erwartet.sort();
+    assert_eq!(geloescht, erwartet);
+
+    let alt = ChatMessageRepository::get_by_id(&db, nachrichten[0])
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(alt.deleted_at.is_none());
+    assert!(FileRepository::list_by_channel(&db, kanal_id)
+        .await
+        .unwrap()
+        .is_empty());
+    assert_eq!(db.get_quota("default").await.unwrap().current_usage, 0);
+
+    let fertig = db.bereinigung_abschliessen(auftrag.id).await.unwrap();
+    assert_eq!((fertig.nachrichten, fertig.dateien), (2, 1));
+    assert!(fertig.completed_at.is_some());
+    // Wiederholen ist harmlos
+    assert!(db
+        .bereinigung_stapel(auftrag.id, 10, "default")
+        .await
+        .unwrap()
+        .ist_leer());
+    assert!(matches!(
+        db.bereinigung_stapel(Uuid::new_v4(), 10, "default").await,
+        Err(DbError::NichtGefunden(_))
+    ));
+}
+
+#[tokio::test]
+async fn bereinigung_nur_in_einem_kanal() {
+    let db = db().await;
+    let (user_id, kanal_id, nachrichten) = konto_anlegen(&db).await;
+    let auftrag = db
+        .bereinigung_anlegen(NeueInhaltsBereinigung {
+            user_id,
+            seit: Utc::now() - Duration::hours(1),
+            channel_id: Some(Uuid::new_v4()),
+            angefordert_von: None,
+        })
+        .await
+        .unwrap();
+    assert!(db
+        .bereinigung_stapel(auftrag.id, 10, "default")
+        .await
+        .unwrap()
+        .ist_leer());
+
+    let auftrag = db
+        .bereinigung_anlegen(NeueInhaltsBereinigung {
+            user_id,
+            seit: Utc::now() - Duration::hours(1),
+            channel_id: Some(kanal_id),
+            angefordert_von: Some(user_id),
+        })
+        .await
+        .unwrap();
+    let stapel = db
+        .bereinigung_stapel(auftrag.id, 10, "default")
+        .await
+        .unwrap();
+    assert_eq!(stapel.nachrichten.len(), nachrichten.len());
+    assert_eq!(stapel.dateien.len(), 1);
+    assert_eq!(
+        db.bereinigung_laden(auftrag.id)
+            .await
+            .unwrap()
+            .unwrap()
+            .angefordert_von,
+        Some(user_id)
+    );
+}
+
+#[tokio::test]
+async fn export_token_nur_einmal_und_einmal_pro_sperrfrist() {
+    let db = db().await;
+    let (user_id, _, _) = konto_anlegen(&db).await;
+    let jetzt = Utc::now();
+    let neu = |token_hash| NeuerKontoExport {
+        user_id,
+        token_hash,
+        storage_path: "exporte/a.json",
+        expires_at: jetzt + Duration::days(1),
+        gesperrt_seit: Some(jetzt - Duration::days(1)),
+    };
+
+    let export = db.export_anlegen(neu("hash-a")).await.unwrap().unwrap();
+    assert!(export.completed_at.is_none());
+    // Zweite Anforderung innerhalb der Sperrfrist wird abgelehnt
+    assert!(db.export_anlegen(neu("hash-b")).await.unwrap().is_none());
+
+    // Nicht fertig: Token noch nicht einloesbar
+    assert!(db
+        .export_einloesen("hash-a", jetzt)
+        .await
+        .unwrap()
+        .is_none());
+    db.export_abschliessen(export.id, 42).await.unwrap();
+
+    let abgeholt = db.export_einloesen("hash-a", jetzt).await.unwrap().unwrap();
+    assert_eq!(abgeholt.size_bytes, Some(42));
+    assert!(db
+        .export_einloesen("hash-a", jetzt)
+        .await
+        .unwrap()
+        .is_none());
+
+    // Ablauf entfernt den Export und gibt den Pfad zum Aufraeumen zurueck
+    let abgelaufen = db
+        .exporte_ablaufen(jetzt + Duration::days(2))
+        .await
+        .unwrap();
+    assert_eq!(abgelaufen.len(), 1);
+    assert_eq!(abgelaufen[0].storage_path, "exporte/a.json");
+}
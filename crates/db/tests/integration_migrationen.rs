@@ -0,0 +1,45 @@
+//! Integration-Tests fuer den Migrations-Fortschritt (SQLite-Datei)
+
+use speakeasy_db::{DatabaseBackend, DatabaseConfig, MigrationsFortschritt, SqliteDb};
+
+fn datei_config() -> (DatabaseConfig, std::path::PathBuf) {
+    let pfad =
+        std::env::temp_dir().join(format!("speakeasy-migration-{}.db", uuid::Uuid::new_v4()));
+    let config = DatabaseConfig {
+        backend: DatabaseBackend::Sqlite,
+        url: format!("sqlite://{}", pfad.display()),
+        max_verbindungen: 2,
+        sqlite_wal: false,
+    };
+    (config, pfad)
+}
+
+#[tokio::test]
+async fn fortschritt_meldet_jede_ausstehende_migration() {
+    let (config, pfad) = datei_config();
+
+    let mut meldungen: Vec<MigrationsFortschritt> = Vec::new();
+    let db = SqliteDb::oeffnen_mit_fortschritt(&config, |f| meldungen.push(f))
+        .await
+        .unwrap();
+    drop(db);
+
+    assert!(!meldungen.is_empty());
+    let gesamt = meldungen.len();
+    for (index, meldung) in meldungen.iter().enumerate() {
+        assert_eq!(meldung.aktuell, index + 1);
+        assert_eq!(meldung.gesamt, gesamt);
+    }
+    assert_eq!(meldungen[0].version, 1);
+    assert!(meldungen.windows(2).all(|w| w[0].version < w[1].version));
+
+    // Zweiter Start: nichts mehr ausstehend
+    let mut zweiter_start = 0;
+    let db = SqliteDb::oeffnen_mit_fortschritt(&config, |_| zweiter_start += 1)
+        .await
+        .unwrap();
+    assert_eq!(zweiter_start, 0);
+    db.pool().close().await;
+
+    let _ = std::fs::remove_file(pfad);
+}
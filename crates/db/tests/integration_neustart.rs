@@ -0,0 +1,84 @@
+//! Integration-Tests fuer Neustart-Protokoll und Shutdown-Marker (In-Memory SQLite)
+
+use std::path::PathBuf;
+
+use speakeasy_db::{
+    neustart::{self, marker_lesen, MarkerZustand},
+    ServerStartRepository, SqliteDb,
+};
+
+async fn db() -> SqliteDb {
+    SqliteDb::in_memory()
+        .await
+        .expect("In-Memory DB konnte nicht erstellt werden")
+}
+
+fn marker() -> (PathBuf, PathBuf) {
+    let verzeichnis =
+        std::env::temp_dir().join(format!("speakeasy-neustart-{}", uuid::Uuid::new_v4()));
+    let pfad = verzeichnis.join("shutdown.marker");
+    (verzeichnis, pfad)
+}
+
+#[tokio::test]
+async fn saubere_und_unsaubere_zyklen() {
+    let db = db().await;
+    let (verzeichnis, pfad) = marker();
+
+    // Erster Start ohne Marker: nichts Verdaechtiges
+    let erster = neustart::start_erfassen(&db, &pfad, "1.0.0", "hash-a")
+        .await
+        .unwrap();
+    assert!(erster.clean_shutdown);
+    assert_eq!(marker_lesen(&pfad), MarkerZustand::Unsauber);
+
+    // Geordnet beendet, dann neu gestartet
+    neustart::sauber_beendet(&pfad).unwrap();
+    let zweiter = neustart::start_erfassen(&db, &pfad, "1.0.1", "hash-b")
+        .await
+        .unwrap();
+    assert!(zweiter.clean_shutdown);
+
+    // Absturz: der Marker steht noch auf "laufend"
+    let dritter = neustart::start_erfassen(&db, &pfad, "1.0.1", "hash-b")
+        .await
+        .unwrap();
+    assert!(!dritter.clean_shutdown);
+
+    let verlauf = db.list_recent(10).await.unwrap();
+    let flags: Vec<_> = verlauf.iter().map(|s| s.clean_shutdown).collect();
+    assert_eq!(flags, [false, true, true]);
+    assert_eq!(verlauf[0].id, dritter.id);
+    assert_eq!(verlauf[2].version, "1.0.0");
+    assert_eq!(verlauf[2].config_hash, "hash-a");
+    assert_eq!(db.list_recent(1).await.unwrap().len(), 1);
+
+    let _ = std::fs::remove_dir_all(verzeichnis);
+}
+
+#[tokio::test]
+async fn beschaedigter_oder_verlorener_marker_gilt_als_unsauber() {
+    let db = db().await;
+    let (verzeichnis, pfad) = marker();
+
+    neustart::start_erfassen(&db, &pfad, "1.0.0", "hash")
+        .await
+        .unwrap();
+    neustart::sauber_beendet(&pfad).unwrap();
+
+    // Halb geschriebener oder zerstoerter Marker
+    std::fs::write(&pfad, b"sau\0\0\0").unwrap();
+    let start = neustart::start_erfassen(&db, &pfad, "1.0.0", "hash")
+        .await
+        .unwrap();
+    assert!(!start.clean_shutdown);
+
+    // Marker fehlt, obwohl es schon Starts gab
+    std::fs::remove_file(&pfad).unwrap();
+    let start = neustart::start_erfassen(&db, &pfad, "1.0.0", "hash")
+        .await
+        .unwrap();
+    assert!(!start.clean_shutdown);
+
+    let _ = std::fs::remove_dir_all(verzeichnis);
+}
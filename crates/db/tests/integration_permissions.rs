@@ -5,6 +5,7 @@ use speakeasy_db::{
         BerechtigungsWert, BerechtigungsZiel, NeueKanalGruppe, NeueServerGruppe, NeuerBenutzer,
         NeuerKanal, TriState,
     },
+    permissions::{BerechtigungsStufe, BERECHTIGUNGS_KATALOG},
     ChannelGroupRepository, ChannelRepository, PermissionRepository, ServerGroupRepository,
     SqliteDb, UserRepository,
 };
@@ -389,3 +390,113 @@ async fn effektive_berechtigungen_kanal_gruppe_vor_server_gruppe() {
         can_kick.quelle
     );
 }
+
+#[tokio::test]
+async fn nachverfolgung_nennt_gewinnende_regel() {
+    let db = db().await;
+
+    let user = UserRepository::create(
+        &db,
+        NeuerBenutzer {
+            username: "spur_user",
+            password_hash: "hash",
+        },
+    )
+    .await
+    .unwrap();
+    let kanal = ChannelRepository::create(
+        &db,
+        NeuerKanal {
+            name: "Spurkanal",
+            ..Default::default()
+        },
+    )
+    .await
+    .unwrap();
+    let moderator = ServerGroupRepository::create(
+        &db,
+        NeueServerGruppe {
+            name: "Moderator",
+            priority: 100,
+            is_default: false,
+        },
+    )
+    .await
+    .unwrap();
+    let kanal_gruppe = ChannelGroupRepository::create(&db, NeueKanalGruppe { name: "Gast" })
+        .await
+        .unwrap();
+    ServerGroupRepository::add_member(&db, moderator.id, user.id)
+        .await
+        .unwrap();
+    ChannelGroupRepository::set_member_group(&db, user.id, kanal.id, kanal_gruppe.id)
+        .await
+        .unwrap();
+
+    let regeln = [
+        // Upload: Server-Default erlaubt, Kanal-Gruppe verbietet
+        (
+            BerechtigungsZiel::ServerDefault,
+            "i_upload_limit",
+            BerechtigungsWert::IntLimit(100),
+            None,
+        ),
+        (
+            BerechtigungsZiel::KanalGruppe(kanal_gruppe.id),
+            "i_upload_limit",
+            BerechtigungsWert::IntLimit(0),
+            Some(kanal.id),
+        ),
+        // Kick: Individual-Skip faellt auf die Server-Gruppe durch
+        (
+            BerechtigungsZiel::Benutzer(user.id),
+            "b_client_kick_channel",
+            BerechtigungsWert::TriState(TriState::Skip),
+            Some(kanal.id),
+        ),
+        (
+            BerechtigungsZiel::ServerGruppe(moderator.id),
+            "b_client_kick_channel",
+            grant(),
+            None,
+        ),
+        (
+            BerechtigungsZiel::KanalDefault(kanal.id),
+            "b_channel_join",
+            deny(),
+            Some(kanal.id),
+        ),
+    ];
+    for (ziel, key, wert, kanal_id) in regeln {
+        PermissionRepository::set_permission(&db, &ziel, key, wert, kanal_id)
+            .await
+            .unwrap();
+    }
+
+    let spuren = PermissionRepository::resolve_with_trace(&db, user.id, kanal.id)
+        .await
+        .unwrap();
+    assert_eq!(spuren.len(), BERECHTIGUNGS_KATALOG.len());
+    let spur = |key: &str| spuren.iter().find(|s| s.permission_key == key).unwrap();
+
+    let upload = spur("i_upload_limit");
+    let entscheidend = upload.entscheidend.as_ref().unwrap();
+    assert_eq!(entscheidend.stufe, BerechtigungsStufe::KanalGruppe);
+    assert_eq!(entscheidend.wert, BerechtigungsWert::IntLimit(0));
+    assert_eq!(upload.regeln.len(), 2);
+
+    let kick = spur("b_client_kick_channel").entscheidend.as_ref().unwrap();
+    assert_eq!(
+        kick.stufe,
+        BerechtigungsStufe::ServerGruppe {
+            name: "Moderator".into()
+        }
+    );
+    assert_eq!(kick.stufe.geltungsbereich(), "server");
+
+    let join = spur("b_channel_join").entscheidend.as_ref().unwrap();
+    assert_eq!(join.stufe, BerechtigungsStufe::KanalDefault);
+    assert_eq!(join.wert, deny());
+
+    assert!(spur("b_server_stop").entscheidend.is_none());
+}
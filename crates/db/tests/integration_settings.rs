@@ -0,0 +1,200 @@
+//! Integration-Tests fuer SettingsRepository und EinstellungsCache (In-Memory SQLite)
+
+use std::sync::Arc;
+
+use speakeasy_db::{
+    einstellungen::{schluessel, EinstellungsAenderung, EinstellungsCache, ServerEinstellungen},
+    models::MAX_BEARBEITUNGSFRIST_SEK,
+    motd::{Motd, MAX_MOTD_BYTES},
+    DbError, SettingsRepository, SqliteDb,
+};
+
+async fn db() -> SqliteDb {
+    SqliteDb::in_memory()
+        .await
+        .expect("In-Memory DB konnte nicht erstellt werden")
+}
+
+fn standard() -> ServerEinstellungen {
+    ServerEinstellungen {
+        name: "Speakeasy".into(),
+        willkommensnachricht: Some("Hallo".into()),
+        max_clients: 512,
+        host_nachricht: None,
+        chat_bearbeitungsfrist_sek: 0,
+    }
+}
+
+#[tokio::test]
+async fn typisierte_zugriffe_mit_standardwerten() {
+    let db = db().await;
+    assert_eq!(db.get("fehlt").await.unwrap(), None);
+    assert_eq!(db.get_string("fehlt", "std").await.unwrap(), "std");
+    assert_eq!(db.get_u32("fehlt", 7).await.unwrap(), 7);
+
+    db.set("zahl", "42").await.unwrap();
+    assert_eq!(db.get_u32("zahl", 7).await.unwrap(), 42);
+    db.set("zahl", "43").await.unwrap();
+    assert_eq!(db.get_string("zahl", "").await.unwrap(), "43");
+
+    db.set("text", "keine zahl").await.unwrap();
+    assert!(matches!(
+        db.get_u32("text", 0).await,
+        Err(DbError::UngueltigeDaten(_))
+    ));
+
+    let schluessel: Vec<_> = SettingsRepository::list(&db)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|e| e.key)
+        .collect();
+    assert_eq!(schluessel, vec!["text", "zahl"]);
+}
+
+#[tokio::test]
+async fn vorbelegen_ueberschreibt_nichts() {
+    let db = db().await;
+    db.set(schluessel::NAME, "Bereits umbenannt").await.unwrap();
+
+    let angelegt = standard().vorbelegen(&db).await.unwrap();
+    assert_eq!(angelegt, 4);
+    assert_eq!(standard().vorbelegen(&db).await.unwrap(), 0);
+
+    let geladen = ServerEinstellungen::laden(&db, &standard()).await.unwrap();
+    assert_eq!(geladen.name, "Bereits umbenannt");
+    assert_eq!(geladen.willkommensnachricht.as_deref(), Some("Hallo"));
+    assert_eq!(geladen.max_clients, 512);
+    assert_eq!(geladen.host_nachricht, None);
+}
+
+#[tokio::test]
+async fn cache_wird_bei_aenderung_verworfen() {
+    let db = Arc::new(db().await);
+    let cache = EinstellungsCache::neu(Arc::clone(&db), standard());
+    assert_eq!(cache.aktuell().await.unwrap(), standard());
+
+    // Direkte Schreibzugriffe umgehen den Cache
+    db.set(schluessel::MAX_CLIENTS, "10").await.unwrap();
+    assert_eq!(cache.aktuell().await.unwrap().max_clients, 512);
+
+    let neu = cache
+        .aendern(&EinstellungsAenderung {
+            willkommensnachricht: Some("Willkommen zurueck".into()),
+            host_nachricht: Some("Wartung um 22 Uhr".into()),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    assert_eq!(
+        neu.willkommensnachricht.as_deref(),
+        Some("Willkommen zurueck")
+    );
+    assert_eq!(neu.host_nachricht.as_deref(), Some("Wartung um 22 Uhr"));
+    assert_eq!(neu.max_clients, 10);
+
+    // Leerer Text entfernt die Nachricht
+    let neu = cache
+        .aendern(&EinstellungsAenderung {
+            willkommensnachricht: Some(String::new()),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    assert_eq!(neu.willkommensnachricht, None);
+
+    let neu = cache
+        .aendern(&EinstellungsAenderung {
+            chat_bearbeitungsfrist_sek: Some(900),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    assert_eq!(neu.chat_bearbeitungsfrist_sek, 900);
+    assert_eq!(
+        db.get(schluessel::CHAT_BEARBEITUNGSFRIST)
+            .await
+            .unwrap()
+            .as_deref(),
+        Some("900")
+    );
+}
+
+#[tokio::test]
+async fn ungueltige_aenderungen_werden_abgelehnt() {
+    let db = Arc::new(db().await);
+    let cache = EinstellungsCache::neu(Arc::clone(&db), standard());
+
+    for aenderung in [
+        EinstellungsAenderung {
+            name: Some("  ".into()),
+            ..Default::default()
+        },
+        EinstellungsAenderung {
+            max_clients: Some(0),
+            ..Default::default()
+        },
+        EinstellungsAenderung {
+            chat_bearbeitungsfrist_sek: Some(MAX_BEARBEITUNGSFRIST_SEK + 1),
+            ..Default::default()
+        },
+    ] {
+        assert!(matches!(
+            cache.aendern(&aenderung).await,
+            Err(DbError::UngueltigeDaten(_))
+        ));
+    }
+    assert!(SettingsRepository::list(db.as_ref())
+        .await
+        .unwrap()
+        .is_empty());
+}
+
+#[tokio::test]
+async fn motd_version_steigt_nur_bei_aenderung() {
+    let db = Arc::new(db().await);
+    let cache = EinstellungsCache::neu(Arc::clone(&db), standard());
+    assert_eq!(cache.motd().await.unwrap(), Motd::default());
+
+    let (motd, geaendert) = cache.motd_setzen("**Hallo**").await.unwrap();
+    assert!(geaendert);
+    assert_eq!(motd.version, 1);
+
+    // Gleicher Text nach der Bereinigung: keine neue Version
+    let (motd, geaendert) = cache.motd_setzen("**Hallo**\r\n\n").await.unwrap();
+    assert!(!geaendert);
+    assert_eq!(motd.version, 1);
+
+    let (motd, geaendert) = cache.motd_setzen("<b>Neu</b>").await.unwrap();
+    assert!(geaendert);
+    assert_eq!(motd.version, 2);
+    assert_eq!(motd.markdown, "&lt;b>Neu&lt;/b>");
+
+    // Leerer Text entfernt die Nachricht, zaehlt aber als Aenderung
+    let (motd, geaendert) = cache.motd_setzen("").await.unwrap();
+    assert!(geaendert && motd.ist_leer());
+    assert_eq!(cache.motd().await.unwrap().version, 3);
+}
+
+#[tokio::test]
+async fn zu_grosse_motd_wird_abgelehnt() {
+    let db = Arc::new(db().await);
+    let cache = EinstellungsCache::neu(Arc::clone(&db), standard());
+    cache.motd_setzen("alt").await.unwrap();
+
+    let genau = "a".repeat(MAX_MOTD_BYTES);
+    assert_eq!(cache.motd_setzen(&genau).await.unwrap().0.version, 2);
+
+    for zu_gross in [
+        "a".repeat(MAX_MOTD_BYTES + 1),
+        "<".repeat(MAX_MOTD_BYTES / 2),
+    ] {
+        assert!(matches!(
+            cache.motd_setzen(&zu_gross).await,
+            Err(DbError::UngueltigeDaten(_))
+        ));
+    }
+    let motd = cache.motd().await.unwrap();
+    assert_eq!(motd.version, 2);
+    assert_eq!(motd.markdown.len(), MAX_MOTD_BYTES);
+}
@@ -0,0 +1,60 @@
+//! Integration-Tests fuer die Online-Sicherung (`VACUUM INTO`)
+
+use speakeasy_db::{
+    models::NeuerBenutzer, DatabaseBackend, DatabaseConfig, SqliteDb, UserRepository,
+};
+
+fn datei_config(pfad: &std::path::Path, sqlite_wal: bool) -> DatabaseConfig {
+    DatabaseConfig {
+        backend: DatabaseBackend::Sqlite,
+        url: format!("sqlite://{}", pfad.display()),
+        max_verbindungen: 2,
+        sqlite_wal,
+    }
+}
+
+#[tokio::test]
+async fn sicherung_enthaelt_daten_und_schema() {
+    let verzeichnis =
+        std::env::temp_dir().join(format!("speakeasy-sicherung-{}", uuid::Uuid::new_v4()));
+    std::fs::create_dir_all(&verzeichnis).unwrap();
+    let db = SqliteDb::oeffnen(&datei_config(&verzeichnis.join("quelle.db"), true))
+        .await
+        .unwrap();
+    UserRepository::create(
+        &db,
+        NeuerBenutzer {
+            username: "gesichert",
+            password_hash: "hash",
+        },
+    )
+    .await
+    .unwrap();
+
+    let ziel = verzeichnis.join("kopie.db");
+    db.sichern_nach(&ziel).await.unwrap();
+
+    // Ein vorhandenes Ziel wird nicht ueberschrieben
+    assert!(db.sichern_nach(&ziel).await.is_err());
+    db.pool().close().await;
+
+    let kopie = SqliteDb::oeffnen(&datei_config(&ziel, false))
+        .await
+        .unwrap();
+    assert!(kopie.get_by_name("gesichert").await.unwrap().is_some());
+    assert_eq!(
+        kopie.schema_version().await.unwrap(),
+        SqliteDb::schema_version_binary()
+    );
+    kopie.pool().close().await;
+
+    let _ = std::fs::remove_dir_all(verzeichnis);
+}
+
+#[tokio::test]
+async fn in_memory_datenbank_wird_abgelehnt() {
+    let db = SqliteDb::in_memory().await.unwrap();
+    let ziel =
+        std::env::temp_dir().join(format!("speakeasy-sicherung-{}.db", uuid::Uuid::new_v4()));
+    assert!(db.sichern_nach(&ziel).await.is_err());
+}
@@ -0,0 +1,101 @@
+//! Integration-Tests fuer SoundboardRepository (In-Memory SQLite)
+
+use speakeasy_db::{
+    models::{NeuerKanal, NeuerSound},
+    ChannelRepository, DbError, SoundboardRepository, SqliteDb,
+};
+
+async fn db() -> SqliteDb {
+    SqliteDb::in_memory()
+        .await
+        .expect("In-Memory DB konnte nicht erstellt werden")
+}
+
+fn sound<'a>(
+    name: &'a str,
+    channel_id: Option<uuid::Uuid>,
+    frames: &'a [Vec<u8>],
+) -> NeuerSound<'a> {
+    NeuerSound {
+        name,
+        channel_id,
+        file_id: None,
+        frame_ms: 20,
+        frames,
+        created_by: None,
+    }
+}
+
+#[tokio::test]
+async fn frames_bleiben_erhalten() {
+    let db = db().await;
+    // Leerer Frame (DTX) und Frame mit maximaler Opus-Groesse
+    let frames = vec![vec![1, 2, 3], Vec::new(), vec![0xAB; 1275]];
+
+    let angelegt = SoundboardRepository::create(&db, sound("Fanfare", None, &frames))
+        .await
+        .unwrap();
+    assert_eq!(angelegt.duration_ms, 60);
+
+    let geladen = SoundboardRepository::get_by_id(&db, angelegt.id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(geladen.name, "Fanfare");
+    assert_eq!(geladen.frame_ms, 20);
+    assert_eq!(
+        SoundboardRepository::get_frames(&db, angelegt.id)
+            .await
+            .unwrap(),
+        Some(frames)
+    );
+
+    assert!(SoundboardRepository::delete(&db, angelegt.id)
+        .await
+        .unwrap());
+    assert!(SoundboardRepository::get_frames(&db, angelegt.id)
+        .await
+        .unwrap()
+        .is_none());
+}
+
+#[tokio::test]
+async fn liste_nach_kanal_und_eindeutige_namen() {
+    let db = db().await;
+    let kanal = ChannelRepository::create(
+        &db,
+        NeuerKanal {
+            name: "Event",
+            ..Default::default()
+        },
+    )
+    .await
+    .unwrap();
+    let frames = vec![vec![0u8; 10]];
+
+    SoundboardRepository::create(&db, sound("Gong", None, &frames))
+        .await
+        .unwrap();
+    SoundboardRepository::create(&db, sound("Countdown", Some(kanal.id), &frames))
+        .await
+        .unwrap();
+    // Gleicher Name im Kanal erlaubt, serverweit nicht doppelt
+    SoundboardRepository::create(&db, sound("Gong", Some(kanal.id), &frames))
+        .await
+        .unwrap();
+    let doppelt = SoundboardRepository::create(&db, sound("Gong", None, &frames)).await;
+    assert!(matches!(doppelt, Err(DbError::Eindeutigkeit(_))));
+
+    let im_kanal = SoundboardRepository::list(&db, Some(kanal.id))
+        .await
+        .unwrap();
+    assert_eq!(im_kanal.len(), 3);
+    let anderswo = SoundboardRepository::list(&db, Some(uuid::Uuid::new_v4()))
+        .await
+        .unwrap();
+    assert_eq!(anderswo.len(), 1);
+    assert_eq!(anderswo[0].channel_id, None);
+
+    let leer = SoundboardRepository::create(&db, sound("Stille", None, &[])).await;
+    assert!(matches!(leer, Err(DbError::UngueltigeDaten(_))));
+}
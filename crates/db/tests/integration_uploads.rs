@@ -0,0 +1,111 @@
+//! Integration-Tests fuer angemeldete Datei-Uploads (In-Memory SQLite)
+
+use chrono::{Duration, Utc};
+use speakeasy_db::{
+    models::{KanalTyp, NeuerBenutzer, NeuerKanal, NeuerUpload},
+    ChannelRepository, FileRepository, SqliteDb, UserRepository,
+};
+use uuid::Uuid;
+
+async fn db() -> SqliteDb {
+    SqliteDb::in_memory()
+        .await
+        .expect("In-Memory DB konnte nicht erstellt werden")
+}
+
+async fn kanal_und_benutzer(db: &SqliteDb) -> (Uuid, Uuid) {
+    let user = UserRepository::create(
+        db,
+        NeuerBenutzer {
+            username: "hochlader",
+            password_hash: "hash",
+        },
+    )
+    .await
+    .unwrap();
+    let kanal = ChannelRepository::create(
+        db,
+        NeuerKanal {
+            name: "dateien",
+            channel_type: KanalTyp::Text,
+            ..Default::default()
+        },
+    )
+    .await
+    .unwrap();
+    (kanal.id, user.id)
+}
+
+#[tokio::test]
+async fn upload_abschliessen_uebernimmt_id_genau_einmal() {
+    let db = db().await;
+    let (channel_id, uploader_id) = kanal_und_benutzer(&db).await;
+
+    let upload = db
+        .create_upload(NeuerUpload {
+            channel_id,
+            uploader_id,
+            filename: "bericht.pdf",
+            mime_type: "application/pdf",
+            size_bytes: 2048,
+            checksum: Some("abc"),
+            expires_at: Utc::now() + Duration::minutes(10),
+        })
+        .await
+        .unwrap();
+    let geladen = db.get_upload(upload.id).await.unwrap().unwrap();
+    assert_eq!(geladen.checksum.as_deref(), Some("abc"));
+    assert_eq!(geladen.size_bytes, 2048);
+    // Noch keine Datei: Liste und Einzelabfrage sehen den Upload nicht
+    assert!(FileRepository::get_by_id(&db, upload.id)
+        .await
+        .unwrap()
+        .is_none());
+    assert!(db.list_by_channel(channel_id).await.unwrap().is_empty());
+
+    let datei = db
+        .complete_upload(upload.id, "kanal/bericht.pdf", "abc")
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(datei.id, upload.id);
+    assert_eq!(datei.filename, "bericht.pdf");
+    assert_eq!(datei.storage_path, "kanal/bericht.pdf");
+    assert!(db.get_upload(upload.id).await.unwrap().is_none());
+    assert_eq!(db.list_by_channel(channel_id).await.unwrap().len(), 1);
+
+    // Zweiter Abschluss findet nichts mehr
+    assert!(db
+        .complete_upload(upload.id, "kanal/bericht.pdf", "abc")
+        .await
+        .unwrap()
+        .is_none());
+}
+
+#[tokio::test]
+async fn abgelaufene_uploads_entfernen() {
+    let db = db().await;
+    let (channel_id, uploader_id) = kanal_und_benutzer(&db).await;
+    let anmelden = |expires_at| NeuerUpload {
+        channel_id,
+        uploader_id,
+        filename: "a.txt",
+        mime_type: "text/plain",
+        size_bytes: 1,
+        checksum: None,
+        expires_at,
+    };
+
+    let alt = db
+        .create_upload(anmelden(Utc::now() - Duration::minutes(1)))
+        .await
+        .unwrap();
+    let neu = db
+        .create_upload(anmelden(Utc::now() + Duration::minutes(10)))
+        .await
+        .unwrap();
+
+    assert_eq!(db.purge_uploads_before(Utc::now()).await.unwrap(), 1);
+    assert!(db.get_upload(alt.id).await.unwrap().is_none());
+    assert!(db.get_upload(neu.id).await.unwrap().is_some());
+}
@@ -0,0 +1,164 @@
+//! Integration-Tests fuer ZeitplanRepository (In-Memory SQLite)
+
+use chrono::{DateTime, Duration, Utc};
+use speakeasy_db::{
+    models::{GeplanteAktion, NeueGeplanteAktion, NeuerBenutzer},
+    zeitplan::Zeitplan,
+    SqliteDb, UserRepository, ZeitplanRepository,
+};
+use uuid::Uuid;
+
+async fn db() -> SqliteDb {
+    SqliteDb::in_memory()
+        .await
+        .expect("In-Memory DB konnte nicht erstellt werden")
+}
+
+async fn benutzer(db: &SqliteDb) -> Uuid {
+    UserRepository::create(
+        db,
+        NeuerBenutzer {
+            username: "planer",
+            password_hash: "hash",
+        },
+    )
+    .await
+    .unwrap()
+    .id
+}
+
+fn utc(text: &str) -> DateTime<Utc> {
+    DateTime::parse_from_rfc3339(text)
+        .unwrap()
+        .with_timezone(&Utc)
+}
+
+#[tokio::test]
+async fn wiederkehrende_aktion_speichern_und_laden() {
+    let db = db().await;
+    let user = benutzer(&db).await;
+    let zeitplan = Zeitplan::wiederkehrend("0 20 * * 5", "Europe/Berlin").unwrap();
+    let scopes = vec!["admin:schedule".to_string()];
+
+    let angelegt = ZeitplanRepository::create(
+        &db,
+        NeueGeplanteAktion {
+            name: "Freitags-Event",
+            aktion: GeplanteAktion::KanalAusVorlage {
+                vorlage_id: Uuid::new_v4(),
+                parent_id: None,
+                name_prefix: Some("Event ".into()),
+                loeschen_nach_min: Some(180),
+            },
+            zeitplan: zeitplan.clone(),
+            next_run_at: utc("2026-10-16T18:00:00Z"),
+            created_by: user,
+            scopes: Some(&scopes),
+        },
+    )
+    .await
+    .unwrap();
+
+    let geladen = ZeitplanRepository::get_by_id(&db, angelegt.id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(geladen.zeitplan, zeitplan);
+    assert_eq!(geladen.next_run_at, Some(utc("2026-10-16T18:00:00Z")));
+    assert_eq!(geladen.scopes, Some(scopes));
+    assert_eq!(geladen.aktion.typ(), "kanal_aus_vorlage");
+    assert!(!geladen.cancelled);
+    assert_eq!(ZeitplanRepository::list(&db).await.unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn faellige_aktion_wird_nur_einmal_beansprucht() {
+    let db = db().await;
+    let user = benutzer(&db).await;
+    let geplant = utc("2026-10-16T18:00:00Z");
+    let aktion = ZeitplanRepository::create(
+        &db,
+        NeueGeplanteAktion {
+            name: "Aufraeumen",
+            aktion: GeplanteAktion::KanaeleLoeschen {
+                kanal_ids: vec![Uuid::new_v4()],
+            },
+            zeitplan: Zeitplan::Einmalig(geplant),
+            next_run_at: geplant,
+            created_by: user,
+            scopes: None,
+        },
+    )
+    .await
+    .unwrap();
+
+    let vorher = geplant - Duration::minutes(1);
+    assert!(ZeitplanRepository::due(&db, vorher)
+        .await
+        .unwrap()
+        .is_empty());
+
+    let jetzt = geplant + Duration::seconds(30);
+    let faellig = ZeitplanRepository::due(&db, jetzt).await.unwrap();
+    assert_eq!(faellig.len(), 1);
+
+    assert!(
+        ZeitplanRepository::claim(&db, aktion.id, geplant, None, jetzt)
+            .await
+            .unwrap()
+    );
+    // Zweiter Versuch fuer dieselbe Ausfuehrung verliert
+    assert!(
+        !ZeitplanRepository::claim(&db, aktion.id, geplant, None, jetzt)
+            .await
+            .unwrap()
+    );
+    assert!(ZeitplanRepository::due(&db, jetzt)
+        .await
+        .unwrap()
+        .is_empty());
+
+    let erledigt = ZeitplanRepository::get_by_id(&db, aktion.id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(erledigt.next_run_at, None);
+    assert_eq!(erledigt.last_run_at, Some(utc("2026-10-16T18:00:30Z")));
+}
+
+#[tokio::test]
+async fn abgebrochene_aktion_ist_nicht_faellig() {
+    let db = db().await;
+    let user = benutzer(&db).await;
+    let geplant = utc("2026-10-16T18:00:00Z");
+    let aktion = ZeitplanRepository::create(
+        &db,
+        NeueGeplanteAktion {
+            name: "Abgesagt",
+            aktion: GeplanteAktion::KanaeleLoeschen {
+                kanal_ids: vec![Uuid::new_v4()],
+            },
+            zeitplan: Zeitplan::Einmalig(geplant),
+            next_run_at: geplant,
+            created_by: user,
+            scopes: None,
+        },
+    )
+    .await
+    .unwrap();
+
+    assert!(ZeitplanRepository::cancel(&db, aktion.id).await.unwrap());
+    assert!(!ZeitplanRepository::cancel(&db, aktion.id).await.unwrap());
+    assert!(!ZeitplanRepository::cancel(&db, Uuid::new_v4())
+        .await
+        .unwrap());
+    assert!(ZeitplanRepository::due(&db, geplant)
+        .await
+        .unwrap()
+        .is_empty());
+    assert!(
+        !ZeitplanRepository::claim(&db, aktion.id, geplant, None, geplant)
+            .await
+            .unwrap()
+    );
+}
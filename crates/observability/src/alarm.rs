@@ -0,0 +1,702 @@
+//! Eingebaute Alarmierung fuer Betreiber
+//!
+//! Regeln vergleichen periodisch einen Messwert (Paketverlust, Datenbank,
+//! Speicherplatz, Auslastung) mit einer Schwelle. Eine Regel loest erst aus,
+//! wenn die Bedingung `dauer_sek` lang ununterbrochen gilt, und meldet sich
+//! genau einmal beim Ausloesen und einmal, wenn die Bedingung wieder entfaellt.
+//!
+//! Wechselt eine Regel innerhalb des Flap-Fensters oefter als erlaubt, gilt
+//! sie als flatternd: Es geht eine einzelne Meldung raus, danach schweigt die
+//! Regel, bis sich ihr Zustand beruhigt hat. Weicht der Zustand dann vom
+//! zuletzt gemeldeten ab, wird er nachgemeldet.
+//!
+//! Der Auswerter kennt keine Uhr und keine Quellen; der Server sammelt die
+//! [`Messwerte`] und stellt die Meldungen zu.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+#[cfg(feature = "metriken")]
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+/// Messgroesse, auf die sich eine Regel bezieht
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AlarmMetrik {
+    /// Verlorene Voice-Pakete seit der letzten Auswertung in Prozent
+    VoicePaketverlust,
+    /// 1 wenn die Datenbank antwortet, sonst 0
+    DatenbankErreichbar,
+    /// Belegung des Datentraegers mit dem Datei-Speicher in Prozent
+    SpeicherBelegung,
+    /// Verbundene Clients in Prozent von `max_clients`
+    ClientAuslastung,
+}
+
+impl fmt::Display for AlarmMetrik {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::VoicePaketverlust => "voice_paketverlust",
+            Self::DatenbankErreichbar => "datenbank_erreichbar",
+            Self::SpeicherBelegung => "speicher_belegung",
+            Self::ClientAuslastung => "client_auslastung",
+        })
+    }
+}
+
+/// Vergleich zwischen Messwert und Schwelle
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AlarmVergleich {
+    /// Wert > Schwelle
+    Ueber,
+    /// Wert >= Schwelle
+    Mindestens,
+    /// Wert < Schwelle
+    Unter,
+}
+
+impl AlarmVergleich {
+    /// Gibt true zurueck wenn `wert` die Bedingung erfuellt
+    pub fn trifft(self, wert: f64, schwelle: f64) -> bool {
+        match self {
+            Self::Ueber => wert > schwelle,
+            Self::Mindestens => wert >= schwelle,
+            Self::Unter => wert < schwelle,
+        }
+    }
+
+    fn zeichen(self) -> &'static str {
+        match self {
+            Self::Ueber => ">",
+            Self::Mindestens => ">=",
+            Self::Unter => "<",
+        }
+    }
+}
+
+/// Schweregrad einer Regel
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AlarmSchwere {
+    Info,
+    Warnung,
+    Kritisch,
+}
+
+impl fmt::Display for AlarmSchwere {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Info => "info",
+            Self::Warnung => "warnung",
+            Self::Kritisch => "kritisch",
+        })
+    }
+}
+
+/// Zustellweg fuer Meldungen einer Regel
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AlarmZiel {
+    /// HTTP-POST an die konfigurierte Webhook-URL
+    Webhook,
+    /// Ereignis des Commanders
+    Commander,
+    /// `ServerAnnouncement` an verbundene Administratoren
+    Ankuendigung,
+}
+
+/// Konfigurierte Alarmregel
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AlarmRegel {
+    /// Eindeutiger Name, erscheint in allen Meldungen
+    pub name: String,
+    pub metrik: AlarmMetrik,
+    pub vergleich: AlarmVergleich,
+    pub schwelle: f64,
+    /// So lange muss die Bedingung ununterbrochen gelten (0 = sofort)
+    #[serde(default)]
+    pub dauer_sek: u64,
+    pub schwere: AlarmSchwere,
+    /// Zustellwege (leer = nur Log)
+    #[serde(default)]
+    pub ziele: Vec<AlarmZiel>,
+}
+
+impl AlarmRegel {
+    /// Mitgelieferte Regeln fuer die haeufigsten Betriebsprobleme
+    pub fn standard() -> Vec<Self> {
+        let alle = vec![
+            AlarmZiel::Commander,
+            AlarmZiel::Ankuendigung,
+            AlarmZiel::Webhook,
+        ];
+        vec![
+            Self {
+                name: "voice_paketverlust".into(),
+                metrik: AlarmMetrik::VoicePaketverlust,
+                vergleich: AlarmVergleich::Ueber,
+                schwelle: 5.0,
+                dauer_sek: 300,
+                schwere: AlarmSchwere::Warnung,
+                ziele: alle.clone(),
+            },
+            Self {
+                name: "datenbank_nicht_erreichbar".into(),
+                metrik: AlarmMetrik::DatenbankErreichbar,
+                vergleich: AlarmVergleich::Unter,
+                schwelle: 1.0,
+                dauer_sek: 30,
+                schwere: AlarmSchwere::Kritisch,
+                ziele: alle.clone(),
+            },
+            Self {
+                name: "speicher_fast_voll".into(),
+                metrik: AlarmMetrik::SpeicherBelegung,
+                vergleich: AlarmVergleich::Ueber,
+                schwelle: 90.0,
+                dauer_sek: 300,
+                schwere: AlarmSchwere::Warnung,
+                ziele: alle,
+            },
+            Self {
+                name: "server_voll".into(),
+                metrik: AlarmMetrik::ClientAuslastung,
+                vergleich: AlarmVergleich::Mindestens,
+                schwelle: 100.0,
+                dauer_sek: 0,
+                schwere: AlarmSchwere::Info,
+                ziele: vec![AlarmZiel::Commander, AlarmZiel::Ankuendigung],
+            },
+        ]
+    }
+}
+
+/// Grenzen der Flap-Unterdrueckung
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FlapGrenzen {
+    /// Zeitraum, in dem Zustandswechsel gezaehlt werden
+    pub fenster: Duration,
+    /// Mehr Wechsel im Fenster gelten als Flattern (0 = nie)
+    pub max_wechsel: usize,
+}
+
+impl Default for FlapGrenzen {
+    fn default() -> Self {
+        Self {
+            fenster: Duration::from_secs(30 * 60),
+            max_wechsel: 4,
+        }
+    }
+}
+
+/// Momentaufnahme der Messwerte fuer eine Auswertung
+///
+/// Fehlt ein Wert (Quelle nicht verfuegbar), behalten Regeln auf dieser
+/// Metrik ihren bisherigen Zustand.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Messwerte(HashMap<AlarmMetrik, f64>);
+
+impl Messwerte {
+    pub fn neu() -> Self {
+        Self::default()
+    }
+
+    /// Setzt einen Messwert (Builder-Variante von [`Messwerte::setzen`])
+    pub fn mit(mut self, metrik: AlarmMetrik, wert: f64) -> Self {
+        self.setzen(metrik, wert);
+        self
+    }
+
+    pub fn setzen(&mut self, metrik: AlarmMetrik, wert: f64) {
+        self.0.insert(metrik, wert);
+    }
+
+    pub fn wert(&self, metrik: AlarmMetrik) -> Option<f64> {
+        self.0.get(&metrik).copied()
+    }
+}
+
+/// Art einer Meldung
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AlarmArt {
+    /// Bedingung gilt seit `dauer_sek`
+    Ausgeloest,
+    /// Bedingung gilt nicht mehr
+    Behoben,
+    /// Regel wechselt zu oft; weitere Meldungen ruhen bis sie sich beruhigt
+    Flattert,
+}
+
+impl fmt::Display for AlarmArt {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Ausgeloest => "ausgeloest",
+            Self::Behoben => "behoben",
+            Self::Flattert => "flattert",
+        })
+    }
+}
+
+/// Zuzustellende Meldung einer Regel
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AlarmMeldung {
+    pub regel: String,
+    pub metrik: AlarmMetrik,
+    pub schwere: AlarmSchwere,
+    pub art: AlarmArt,
+    pub wert: f64,
+    pub vergleich: AlarmVergleich,
+    pub schwelle: f64,
+    pub ziele: Vec<AlarmZiel>,
+}
+
+impl AlarmMeldung {
+    /// Einzeiliger Text fuer Ankuendigungen und Logs
+    pub fn text(&self) -> String {
+        format!(
+            "[{}] {} {}: {} = {:.2} (Schwelle {} {})",
+            self.schwere,
+            self.regel,
+            self.art,
+            self.metrik,
+            self.wert,
+            self.vergleich.zeichen(),
+            self.schwelle
+        )
+    }
+}
+
+/// Zustand einer Regel fuer die Abfrage per Commander
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AlarmZustand {
+    /// Noch kein Messwert
+    Unbekannt,
+    /// Bedingung gilt nicht
+    Ok,
+    /// Bedingung gilt, `dauer_sek` aber noch nicht erreicht
+    Ausstehend,
+    /// Regel hat ausgeloest
+    Ausgeloest,
+}
+
+/// Aktueller Stand einer Regel
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AlarmStatus {
+    pub regel: String,
+    pub metrik: AlarmMetrik,
+    pub schwere: AlarmSchwere,
+    pub zustand: AlarmZustand,
+    pub flattert: bool,
+    /// Zuletzt gemessener Wert
+    pub wert: Option<f64>,
+    pub vergleich: AlarmVergleich,
+    pub schwelle: f64,
+    /// Sekunden seit Beginn des aktuellen Zustands (ausstehend/ausgeloest)
+    pub seit_sek: Option<u64>,
+}
+
+/// Laufzeitzustand einer Regel
+#[derive(Debug)]
+struct RegelLauf {
+    regel: AlarmRegel,
+    wert: Option<f64>,
+    /// Beginn der ununterbrochen geltenden Bedingung
+    bedingung_seit: Option<Instant>,
+    /// Zeitpunkt des Ausloesens
+    ausgeloest_seit: Option<Instant>,
+    /// Zuletzt gemeldeter Zustand (true = ausgeloest)
+    gemeldet: bool,
+    /// Zeitpunkte der Zustandswechsel im Flap-Fenster
+    wechsel: VecDeque<Instant>,
+    flattert: bool,
+}
+
+impl RegelLauf {
+    fn neu(regel: AlarmRegel) -> Self {
+        Self {
+            regel,
+            wert: None,
+            bedingung_seit: None,
+            ausgeloest_seit: None,
+            gemeldet: false,
+            wechsel: VecDeque::new(),
+            flattert: false,
+        }
+    }
+
+    fn meldung(&self, art: AlarmArt, wert: f64) -> AlarmMeldung {
+        AlarmMeldung {
+            regel: self.regel.name.clone(),
+            metrik: self.regel.metrik,
+            schwere: self.regel.schwere,
+            art,
+            wert,
+            vergleich: self.regel.vergleich,
+            schwelle: self.regel.schwelle,
+            ziele: self.regel.ziele.clone(),
+        }
+    }
+
+    fn auswerten(&mut self, wert: f64, jetzt: Instant, flap: FlapGrenzen) -> Option<AlarmMeldung> {
+        self.wert = Some(wert);
+        let vorher = self.ausgeloest_seit.is_some();
+
+        if self.regel.vergleich.trifft(wert, self.regel.schwelle) {
+            let seit = *self.bedingung_seit.get_or_insert(jetzt);
+            let dauer = Duration::from_secs(self.regel.dauer_sek);
+            if !vorher && jetzt.saturating_duration_since(seit) >= dauer {
+                self.ausgeloest_seit = Some(jetzt);
+            }
+        } else {
+            self.bedingung_seit = None;
+            self.ausgeloest_seit = None;
+        }
+
+        let ausgeloest = self.ausgeloest_seit.is_some();
+        if ausgeloest != vorher {
+            self.wechsel.push_back(jetzt);
+        }
+        while self
+            .wechsel
+            .front()
+            .is_some_and(|&t| jetzt.saturating_duration_since(t) > flap.fenster)
+        {
+            self.wechsel.pop_front();
+        }
+
+        let flattert = flap.max_wechsel > 0 && self.wechsel.len() > flap.max_wechsel;
+        let begonnen = flattert && !self.flattert;
+        self.flattert = flattert;
+        if begonnen {
+            return Some(self.meldung(AlarmArt::Flattert, wert));
+        }
+        if flattert || ausgeloest == self.gemeldet {
+            return None;
+        }
+        self.gemeldet = ausgeloest;
+        let art = if ausgeloest {
+            AlarmArt::Ausgeloest
+        } else {
+            AlarmArt::Behoben
+        };
+        Some(self.meldung(art, wert))
+    }
+
+    fn status(&self, jetzt: Instant) -> AlarmStatus {
+        let (zustand, seit) = match (self.wert, self.ausgeloest_seit, self.bedingung_seit) {
+            (None, _, _) => (AlarmZustand::Unbekannt, None),
+            (_, Some(seit), _) => (AlarmZustand::Ausgeloest, Some(seit)),
+            (_, None, Some(seit)) => (AlarmZustand::Ausstehend, Some(seit)),
+            (_, None, None) => (AlarmZustand::Ok, None),
+        };
+        AlarmStatus {
+            regel: self.regel.name.clone(),
+            metrik: self.regel.metrik,
+            schwere: self.regel.schwere,
+            zustand,
+            flattert: self.flattert,
+            wert: self.wert,
+            vergleich: self.regel.vergleich,
+            schwelle: self.regel.schwelle,
+            seit_sek: seit.map(|s| jetzt.saturating_duration_since(s).as_secs()),
+        }
+    }
+}
+
+/// Wertet Alarmregeln gegen Messwerte aus und fuehrt ihren Zustand
+#[derive(Debug)]
+pub struct AlarmAuswerter {
+    regeln: Vec<RegelLauf>,
+    flap: FlapGrenzen,
+}
+
+impl AlarmAuswerter {
+    pub fn neu(regeln: Vec<AlarmRegel>, flap: FlapGrenzen) -> Self {
+        Self {
+            regeln: regeln.into_iter().map(RegelLauf::neu).collect(),
+            flap,
+        }
+    }
+
+    /// Wertet alle Regeln aus und liefert die zuzustellenden Meldungen
+    pub fn auswerten(&mut self, messwerte: &Messwerte, jetzt: Instant) -> Vec<AlarmMeldung> {
+        let flap = self.flap;
+        self.regeln
+            .iter_mut()
+            .filter_map(|lauf| {
+                let wert = messwerte.wert(lauf.regel.metrik)?;
+                lauf.auswerten(wert, jetzt, flap)
+            })
+            .collect()
+    }
+
+    /// Aktueller Stand aller Regeln
+    pub fn status(&self, jetzt: Instant) -> Vec<AlarmStatus> {
+        self.regeln.iter().map(|lauf| lauf.status(jetzt)).collect()
+    }
+}
+
+/// Belegung des Datentraegers, auf dem `pfad` liegt, in Prozent
+///
+/// Massgeblich ist der laengste Einhaengepunkt, unter dem der Pfad liegt.
+/// `None`, wenn der Pfad nicht existiert oder kein Datentraeger passt.
+#[cfg(feature = "metriken")]
+pub fn speicher_belegung(pfad: &Path) -> Option<f64> {
+    let pfad = pfad.canonicalize().ok()?;
+    let disks = sysinfo::Disks::new_with_refreshed_list();
+    let disk = disks
+        .list()
+        .iter()
+        .filter(|d| pfad.starts_with(d.mount_point()))
+        .max_by_key(|d| d.mount_point().as_os_str().len())?;
+    let gesamt = disk.total_space();
+    if gesamt == 0 {
+        return None;
+    }
+    let belegt = gesamt.saturating_sub(disk.available_space());
+    Some(belegt as f64 * 100.0 / gesamt as f64)
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn regel(dauer_sek: u64) -> AlarmRegel {
+        AlarmRegel {
+            name: "verlust".into(),
+            metrik: AlarmMetrik::VoicePaketverlust,
+            vergleich: AlarmVergleich::Ueber,
+            schwelle: 5.0,
+            dauer_sek,
+            schwere: AlarmSchwere::Warnung,
+            ziele: vec![AlarmZiel::Commander],
+        }
+    }
+
+    fn verlust(wert: f64) -> Messwerte {
+        Messwerte::neu().mit(AlarmMetrik::VoicePaketverlust, wert)
+    }
+
+    fn arten(meldungen: &[AlarmMeldung]) -> Vec<AlarmArt> {
+        meldungen.iter().map(|m| m.art).collect()
+    }
+
+    #[test]
+    fn loest_erst_nach_dauer_aus_und_nur_einmal() {
+        let start = Instant::now();
+        let mut auswerter = AlarmAuswerter::neu(vec![regel(300)], FlapGrenzen::default());
+
+        assert!(auswerter.auswerten(&verlust(8.0), start).is_empty());
+        let status = auswerter.status(start + Duration::from_secs(60));
+        assert_eq!(status[0].zustand, AlarmZustand::Ausstehend);
+        assert_eq!(status[0].seit_sek, Some(60));
+
+        let t = start + Duration::from_secs(299);
+        assert!(auswerter.auswerten(&verlust(9.0), t).is_empty());
+
+        let t = start + Duration::from_secs(300);
+        let meldungen = auswerter.auswerten(&verlust(9.0), t);
+        assert_eq!(arten(&meldungen), [AlarmArt::Ausgeloest]);
+        assert_eq!(meldungen[0].wert, 9.0);
+        assert!(meldungen[0].text().contains("[warnung] verlust ausgeloest"));
+
+        // Solange die Bedingung gilt, keine weitere Meldung
+        for s in 301..310 {
+            let t = start + Duration::from_secs(s);
+            assert!(auswerter.auswerten(&verlust(12.0), t).is_empty());
+        }
+        assert_eq!(auswerter.status(t)[0].zustand, AlarmZustand::Ausgeloest);
+    }
+
+    #[test]
+    fn unterbrechung_setzt_die_dauer_zurueck() {
+        let start = Instant::now();
+        let mut auswerter = AlarmAuswerter::neu(vec![regel(300)], FlapGrenzen::default());
+
+        auswerter.auswerten(&verlust(8.0), start);
+        auswerter.auswerten(&verlust(1.0), start + Duration::from_secs(200));
+        auswerter.auswerten(&verlust(8.0), start + Duration::from_secs(250));
+        // 300 s nach dem ersten Ueberschreiten, aber nur 50 s am Stueck
+        assert!(auswerter
+            .auswerten(&verlust(8.0), start + Duration::from_secs(300))
+            .is_empty());
+        let meldungen = auswerter.auswerten(&verlust(8.0), start + Duration::from_secs(550));
+        assert_eq!(arten(&meldungen), [AlarmArt::Ausgeloest]);
+    }
+
+    #[test]
+    fn behoben_wird_einmal_gemeldet() {
+        let start = Instant::now();
+        let mut auswerter = AlarmAuswerter::neu(vec![regel(0)], FlapGrenzen::default());
+
+        assert_eq!(
+            arten(&auswerter.auswerten(&verlust(8.0), start)),
+            [AlarmArt::Ausgeloest]
+        );
+        let t = start + Duration::from_secs(10);
+        let meldungen = auswerter.auswerten(&verlust(2.0), t);
+        assert_eq!(arten(&meldungen), [AlarmArt::Behoben]);
+        assert_eq!(meldungen[0].wert, 2.0);
+        assert!(auswerter
+            .auswerten(&verlust(1.0), t + Duration::from_secs(10))
+            .is_empty());
+        assert_eq!(auswerter.status(t)[0].zustand, AlarmZustand::Ok);
+    }
+
+    #[test]
+    fn ausstehende_regel_meldet_kein_behoben() {
+        let start = Instant::now();
+        let mut auswerter = AlarmAuswerter::neu(vec![regel(300)], FlapGrenzen::default());
+
+        auswerter.auswerten(&verlust(8.0), start);
+        assert!(auswerter
+            .auswerten(&verlust(1.0), start + Duration::from_secs(100))
+            .is_empty());
+    }
+
+    #[test]
+    fn fehlender_messwert_haelt_den_zustand() {
+        let start = Instant::now();
+        let mut auswerter = AlarmAuswerter::neu(vec![regel(0)], FlapGrenzen::default());
+
+        assert_eq!(auswerter.status(start)[0].zustand, AlarmZustand::Unbekannt);
+        auswerter.auswerten(&verlust(8.0), start);
+        let t = start + Duration::from_secs(10);
+        assert!(auswerter.auswerten(&Messwerte::neu(), t).is_empty());
+        assert_eq!(auswerter.status(t)[0].zustand, AlarmZustand::Ausgeloest);
+    }
+
+    #[test]
+    fn flatternde_regel_schweigt_bis_sie_sich_beruhigt() {
+        let start = Instant::now();
+        let flap = FlapGrenzen {
+            fenster: Duration::from_secs(600),
+            max_wechsel: 4,
+        };
+        let mut auswerter = AlarmAuswerter::neu(vec![regel(0)], flap);
+        let mut gemeldet = Vec::new();
+        let mut t = start;
+
+        // Zehn Wechsel im Minutentakt
+        for i in 0..10 {
+            let wert = if i % 2 == 0 { 8.0 } else { 1.0 };
+            gemeldet.extend(arten(&auswerter.auswerten(&verlust(wert), t)));
+            t += Duration::from_secs(60);
+        }
+        assert_eq!(
+            gemeldet,
+            [
+                AlarmArt::Ausgeloest,
+                AlarmArt::Behoben,
+                AlarmArt::Ausgeloest,
+                AlarmArt::Behoben,
+                AlarmArt::Flattert,
+            ]
+        );
+        assert!(auswerter.status(t)[0].flattert);
+
+        // Bedingung gilt jetzt dauerhaft; gemeldet wird erst, wenn die
+        // Wechsel aus dem Fenster gefallen sind
+        let mut nachgemeldet = Vec::new();
+        for _ in 0..15 {
+            let meldungen = auswerter.auswerten(&verlust(8.0), t);
+            nachgemeldet.extend(meldungen.iter().map(|m| (m.art, t - start)));
+            t += Duration::from_secs(60);
+        }
+        assert_eq!(nachgemeldet.len(), 1);
+        let (art, wann) = nachgemeldet[0];
+        assert_eq!(art, AlarmArt::Ausgeloest);
+        assert!(wann > Duration::from_secs(600));
+        assert!(!auswerter.status(t)[0].flattert);
+    }
+
+    #[test]
+    fn beruhigte_regel_im_gemeldeten_zustand_bleibt_still() {
+        let start = Instant::now();
+        let flap = FlapGrenzen {
+            fenster: Duration::from_secs(600),
+            max_wechsel: 2,
+        };
+        let mut auswerter = AlarmAuswerter::neu(vec![regel(0)], flap);
+        let mut gemeldet = Vec::new();
+        let mut t = start;
+
+        // Endet im Zustand "ok", der zuletzt auch so gemeldet wurde
+        for wert in [8.0, 1.0, 8.0, 1.0] {
+            gemeldet.extend(arten(&auswerter.auswerten(&verlust(wert), t)));
+            t += Duration::from_secs(30);
+        }
+        for _ in 0..30 {
+            gemeldet.extend(arten(&auswerter.auswerten(&verlust(1.0), t)));
+            t += Duration::from_secs(60);
+        }
+        assert_eq!(
+            gemeldet,
+            [AlarmArt::Ausgeloest, AlarmArt::Behoben, AlarmArt::Flattert]
+        );
+        assert!(!auswerter.status(t)[0].flattert);
+    }
+
+    #[test]
+    fn regeln_werden_unabhaengig_ausgewertet() {
+        let start = Instant::now();
+        let mut auswerter = AlarmAuswerter::neu(AlarmRegel::standard(), FlapGrenzen::default());
+        let messwerte = Messwerte::neu()
+            .mit(AlarmMetrik::VoicePaketverlust, 0.5)
+            .mit(AlarmMetrik::DatenbankErreichbar, 0.0)
+            .mit(AlarmMetrik::SpeicherBelegung, 42.0)
+            .mit(AlarmMetrik::ClientAuslastung, 100.0);
+
+        let meldungen = auswerter.auswerten(&messwerte, start);
+        let namen: Vec<_> = meldungen.iter().map(|m| m.regel.as_str()).collect();
+        assert_eq!(namen, ["server_voll"]);
+
+        let meldungen = auswerter.auswerten(&messwerte, start + Duration::from_secs(30));
+        assert_eq!(meldungen.len(), 1);
+        assert_eq!(meldungen[0].regel, "datenbank_nicht_erreichbar");
+        assert_eq!(meldungen[0].schwere, AlarmSchwere::Kritisch);
+
+        let zustaende: Vec<_> = auswerter
+            .status(start + Duration::from_secs(30))
+            .into_iter()
+            .map(|s| s.zustand)
+            .collect();
+        assert_eq!(
+            zustaende,
+            [
+                AlarmZustand::Ok,
+                AlarmZustand::Ausgeloest,
+                AlarmZustand::Ok,
+                AlarmZustand::Ausgeloest,
+            ]
+        );
+    }
+
+    #[test]
+    fn regel_aus_json() {
+        let regel: AlarmRegel = serde_json::from_str(
+            r#"{"name":"x","metrik":"speicher_belegung","vergleich":"ueber","schwelle":80,"schwere":"kritisch"}"#,
+        )
+        .unwrap();
+        assert_eq!(regel.dauer_sek, 0);
+        assert!(regel.ziele.is_empty());
+        assert_eq!(regel.metrik, AlarmMetrik::SpeicherBelegung);
+    }
+
+    #[test]
+    #[cfg(feature = "metriken")]
+    fn speicher_belegung_fuer_unbekannten_pfad() {
+        assert_eq!(
+            speicher_belegung(Path::new("/gibt/es/sicher/nicht/speakeasy")),
+            None
+        );
+    }
+}
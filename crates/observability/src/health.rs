@@ -1,13 +1,30 @@
 //! Health-Check-Endpunkt fuer Speakeasy
 //!
 //! Endpoint: `GET /health`
-//! Response: JSON mit Status, Version, Uptime und DB-Verbindungsstatus
+//! Response: JSON mit Status, Version, Uptime, DB-Verbindungsstatus und dem
+//! Zustand jeder registrierten Komponente (`components`). `200` nur wenn
+//! alles gesund ist, sonst `503`.
+//!
+//! Subsysteme koennen zusaetzliche Pruefungen registrieren; der schlechteste
+//! Status aller Pruefungen bestimmt die Antwort. Faellt eine Komponente der
+//! [`HealthRegistry`] aus, meldet der Server `degraded`: die uebrigen
+//! Subsysteme arbeiten weiter.
+//!
+//! Endpoint: `GET /health/ready` (auch `GET /ready`)
+//! Response: Bereitschaft waehrend des Starts (`starting` mit aktueller
+//! Phase, `ok` oder `failed` mit Grund). Der Server startet den Endpunkt vor
+//! allen anderen Subsystemen, damit Orchestratoren lange Migrationen nicht
+//! fuer einen Haenger halten.
 
 use axum::{extract::State, http::StatusCode, response::IntoResponse, routing::get, Json, Router};
 use serde::{Deserialize, Serialize};
-use std::sync::Arc;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::sync::{Arc, RwLock};
 use std::time::Instant;
 
+use crate::komponenten::{HealthRegistry, KomponentenStatus};
+
 /// Status des Health-Checks
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "lowercase")]
@@ -17,6 +34,28 @@ pub enum HealthStatus {
     Unhealthy,
 }
 
+impl HealthStatus {
+    /// Gibt den schlechteren der beiden Status zurueck
+    pub fn schlechter(self, andere: HealthStatus) -> HealthStatus {
+        if andere.schwere() > self.schwere() {
+            andere
+        } else {
+            self
+        }
+    }
+
+    fn schwere(&self) -> u8 {
+        match self {
+            Self::Healthy => 0,
+            Self::Degraded => 1,
+            Self::Unhealthy => 2,
+        }
+    }
+}
+
+/// Registrierte Zusatzpruefung eines Subsystems
+pub type HealthPruefung = Arc<dyn Fn() -> HealthStatus + Send + Sync>;
+
 /// Antwort des Health-Check-Endpunkts
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct HealthResponse {
@@ -24,6 +63,68 @@ pub struct HealthResponse {
     pub version: String,
     pub uptime_seconds: u64,
     pub db_connected: bool,
+    /// Zustand der registrierten Komponenten nach Name
+    #[serde(default)]
+    pub components: BTreeMap<String, KomponentenStatus>,
+}
+
+/// Phase des Serverstarts
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum StartPhase {
+    /// Prozess laeuft, Datenbank noch nicht geoeffnet
+    #[default]
+    Startet,
+    /// Schema-Migration `aktuell` von `gesamt` laeuft
+    SchemaMigration { aktuell: usize, gesamt: usize },
+    /// Services werden erstellt
+    DiensteErstellen,
+    /// Listener werden gebunden
+    ListenerBinden,
+    /// Startsequenz durchlaufen; bereit sobald alle Subsysteme gemeldet haben
+    Abgeschlossen,
+}
+
+impl fmt::Display for StartPhase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Startet => f.write_str("starting"),
+            Self::SchemaMigration { aktuell, gesamt } => {
+                write!(f, "migrating schema {aktuell} of {gesamt}")
+            }
+            Self::DiensteErstellen => f.write_str("creating services"),
+            Self::ListenerBinden => f.write_str("binding listeners"),
+            Self::Abgeschlossen => f.write_str("waiting for subsystems"),
+        }
+    }
+}
+
+/// Bereitschaft des Servers
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ReadyStatus {
+    Starting,
+    Ok,
+    Failed,
+}
+
+/// Antwort des Readiness-Endpunkts
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ReadyResponse {
+    pub status: ReadyStatus,
+    /// Aktuelle Startphase bzw. Fehlergrund
+    pub detail: String,
+    /// Subsysteme, die noch nicht bereit gemeldet haben
+    #[serde(default)]
+    pub pending: Vec<String>,
+    pub uptime_seconds: u64,
+}
+
+/// Fortschritt des Serverstarts
+#[derive(Debug, Default)]
+struct StartZustand {
+    phase: StartPhase,
+    ausstehend: BTreeSet<String>,
+    fehler: Option<String>,
 }
 
 /// Geteilter Zustand fuer den Health-Check-Handler
@@ -31,6 +132,9 @@ pub struct HealthResponse {
 pub struct HealthState {
     pub start_time: Arc<Instant>,
     pub db_connected: Arc<std::sync::atomic::AtomicBool>,
+    pruefungen: Arc<RwLock<Vec<(String, HealthPruefung)>>>,
+    komponenten: HealthRegistry,
+    start: Arc<RwLock<StartZustand>>,
 }
 
 impl HealthState {
@@ -38,6 +142,9 @@ impl HealthState {
         Self {
             start_time: Arc::new(Instant::now()),
             db_connected: Arc::new(std::sync::atomic::AtomicBool::new(true)),
+            pruefungen: Arc::new(RwLock::new(Vec::new())),
+            komponenten: HealthRegistry::neu(),
+            start: Arc::new(RwLock::new(StartZustand::default())),
         }
     }
 
@@ -53,13 +160,112 @@ impl HealthState {
         self.db_connected
             .store(verbunden, std::sync::atomic::Ordering::Relaxed);
     }
+
+    /// Registriert eine zusaetzliche Pruefung
+    ///
+    /// Pruefungen laufen bei jedem Aufruf von `/health` und sollten daher
+    /// nur bereits vorliegende Zustaende auswerten.
+    pub fn pruefung_registrieren<F>(&self, name: impl Into<String>, pruefung: F)
+    where
+        F: Fn() -> HealthStatus + Send + Sync + 'static,
+    {
+        self.pruefungen
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .push((name.into(), Arc::new(pruefung)));
+    }
+
+    /// Fuehrt alle registrierten Pruefungen aus und liefert den schlechtesten Status
+    pub fn pruefungen_auswerten(&self) -> HealthStatus {
+        // Kopie, damit eine panische Pruefung keine Sperre haelt
+        let pruefungen: Vec<_> = self
+            .pruefungen
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone();
+
+        pruefungen
+            .iter()
+            .fold(HealthStatus::Healthy, |gesamt, (name, pruefung)| {
+                let status = pruefung();
+                if status != HealthStatus::Healthy {
+                    tracing::debug!(pruefung = %name, ?status, "Health-Pruefung nicht gesund");
+                }
+                gesamt.schlechter(status)
+            })
+    }
+
+    /// Komponenten, deren Zustand `/health` einzeln meldet
+    pub fn komponenten(&self) -> &HealthRegistry {
+        &self.komponenten
+    }
+
+    /// Setzt die aktuelle Startphase
+    pub fn phase_setzen(&self, phase: StartPhase) {
+        tracing::info!(phase = %phase, "Startphase");
+        self.start_schreiben(|start| start.phase = phase);
+    }
+
+    /// Aktuelle Startphase
+    pub fn phase(&self) -> StartPhase {
+        self.start.read().unwrap_or_else(|e| e.into_inner()).phase
+    }
+
+    /// Kuendigt ein Subsystem an, ohne dessen Bereitmeldung der Server nicht bereit ist
+    pub fn subsystem_erwarten(&self, name: impl Into<String>) {
+        let name = name.into();
+        self.start_schreiben(|start| {
+            start.ausstehend.insert(name);
+        });
+    }
+
+    /// Meldet ein angekuendigtes Subsystem als bereit
+    pub fn subsystem_bereit(&self, name: &str) {
+        self.start_schreiben(|start| {
+            start.ausstehend.remove(name);
+        });
+    }
+
+    /// Haelt einen Startfehler fest; `/health/ready` meldet danach `failed`
+    pub fn start_fehlgeschlagen(&self, grund: impl Into<String>) {
+        let grund = grund.into();
+        self.start_schreiben(|start| start.fehler = Some(grund));
+    }
+
+    /// Wertet den Startfortschritt fuer `/health/ready` aus
+    pub fn bereitschaft(&self) -> ReadyResponse {
+        let start = self.start.read().unwrap_or_else(|e| e.into_inner());
+        let (status, detail) = match (&start.fehler, start.phase) {
+            (Some(grund), _) => (ReadyStatus::Failed, grund.clone()),
+            (None, StartPhase::Abgeschlossen) if start.ausstehend.is_empty() => {
+                (ReadyStatus::Ok, "ready".to_string())
+            }
+            (None, phase) => (ReadyStatus::Starting, phase.to_string()),
+        };
+        ReadyResponse {
+            status,
+            detail,
+            pending: start.ausstehend.iter().cloned().collect(),
+            uptime_seconds: self.uptime_seconds(),
+        }
+    }
+
+    fn start_schreiben(&self, aenderung: impl FnOnce(&mut StartZustand)) {
+        aenderung(&mut self.start.write().unwrap_or_else(|e| e.into_inner()));
+    }
 }
 
 /// Axum-Router fuer den `/health`-Endpunkt
 pub fn health_router() -> Router {
-    let state = HealthState::neu();
+    health_router_mit_zustand(HealthState::neu())
+}
+
+/// Axum-Router fuer `/health`, `/health/ready` und `/ready` mit vorgegebenem Zustand
+pub fn health_router_mit_zustand(state: HealthState) -> Router {
     Router::new()
         .route("/health", get(health_handler))
+        .route("/health/ready", get(ready_handler))
+        .route("/ready", get(ready_handler))
         .with_state(state)
 }
 
@@ -70,12 +276,22 @@ async fn health_handler(State(state): State<HealthState>) -> impl IntoResponse {
         HealthStatus::Healthy
     } else {
         HealthStatus::Degraded
+    }
+    .schlechter(state.pruefungen_auswerten());
+
+    let components = state.komponenten().auswerten().await;
+    let status = if components
+        .values()
+        .all(|k| k.status == HealthStatus::Healthy)
+    {
+        status
+    } else {
+        status.schlechter(HealthStatus::Degraded)
     };
 
     let http_status = match status {
         HealthStatus::Healthy => StatusCode::OK,
-        HealthStatus::Degraded => StatusCode::OK, // 200 auch bei degraded (Probe soll nicht failen)
-        HealthStatus::Unhealthy => StatusCode::SERVICE_UNAVAILABLE,
+        HealthStatus::Degraded | HealthStatus::Unhealthy => StatusCode::SERVICE_UNAVAILABLE,
     };
 
     let response = HealthResponse {
@@ -83,11 +299,22 @@ async fn health_handler(State(state): State<HealthState>) -> impl IntoResponse {
         version: env!("CARGO_PKG_VERSION").to_string(),
         uptime_seconds: state.uptime_seconds(),
         db_connected,
+        components,
     };
 
     (http_status, Json(response))
 }
 
+/// `GET /health/ready` – `200` erst wenn der Start vollstaendig abgeschlossen ist
+async fn ready_handler(State(state): State<HealthState>) -> impl IntoResponse {
+    let response = state.bereitschaft();
+    let http_status = match response.status {
+        ReadyStatus::Ok => StatusCode::OK,
+        ReadyStatus::Starting | ReadyStatus::Failed => StatusCode::SERVICE_UNAVAILABLE,
+    };
+    (http_status, Json(response))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -115,6 +342,48 @@ mod tests {
         assert!(state.db_verbunden());
     }
 
+    #[test]
+    fn schlechteste_pruefung_bestimmt_status() {
+        let state = HealthState::neu();
+        assert_eq!(state.pruefungen_auswerten(), HealthStatus::Healthy);
+
+        state.pruefung_registrieren("voice", || HealthStatus::Degraded);
+        assert_eq!(state.pruefungen_auswerten(), HealthStatus::Degraded);
+
+        state.pruefung_registrieren("signaling", || HealthStatus::Unhealthy);
+        state.pruefung_registrieren("commander", || HealthStatus::Healthy);
+        assert_eq!(state.pruefungen_auswerten(), HealthStatus::Unhealthy);
+    }
+
+    #[test]
+    fn bereit_erst_nach_allen_subsystemen() {
+        let state = HealthState::neu();
+        assert_eq!(state.bereitschaft().status, ReadyStatus::Starting);
+        assert_eq!(state.bereitschaft().detail, "starting");
+
+        state.subsystem_erwarten("voice");
+        state.subsystem_erwarten("signaling");
+        state.phase_setzen(StartPhase::SchemaMigration {
+            aktuell: 2,
+            gesamt: 5,
+        });
+        assert_eq!(state.bereitschaft().detail, "migrating schema 2 of 5");
+
+        state.phase_setzen(StartPhase::Abgeschlossen);
+        state.subsystem_bereit("voice");
+        let antwort = state.bereitschaft();
+        assert_eq!(antwort.status, ReadyStatus::Starting);
+        assert_eq!(antwort.pending, vec!["signaling"]);
+
+        state.subsystem_bereit("signaling");
+        assert_eq!(state.bereitschaft().status, ReadyStatus::Ok);
+
+        state.start_fehlgeschlagen("Port belegt");
+        let antwort = state.bereitschaft();
+        assert_eq!(antwort.status, ReadyStatus::Failed);
+        assert_eq!(antwort.detail, "Port belegt");
+    }
+
     #[test]
     fn health_response_serialisierung() {
         let response = HealthResponse {
@@ -122,6 +391,7 @@ mod tests {
             version: "0.1.0".to_string(),
             uptime_seconds: 3600,
             db_connected: true,
+            components: BTreeMap::new(),
         };
 
         let json = serde_json::to_string(&response).unwrap();
@@ -138,6 +408,7 @@ mod tests {
             version: "0.1.0".to_string(),
             uptime_seconds: 120,
             db_connected: false,
+            components: BTreeMap::new(),
         };
 
         let json = serde_json::to_string(&response).unwrap();
@@ -154,5 +425,6 @@ mod tests {
         assert_eq!(response.version, "0.1.0");
         assert_eq!(response.uptime_seconds, 100);
         assert!(response.db_connected);
+        assert!(response.components.is_empty());
     }
 }
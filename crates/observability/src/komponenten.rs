@@ -0,0 +1,332 @@
+//! Komponenten-Pruefungen fuer `/health`
+//!
+//! Subsysteme melden ihren Zustand auf zwei Wegen an die [`HealthRegistry`]:
+//! - asynchrone Pruefungen, die bei jedem Aufruf von `/health` laufen
+//!   (z.B. `SELECT 1` gegen die Datenbank), jeweils mit Zeitlimit
+//! - Herzschlaege: die Loop eines Subsystems schreibt regelmaessig die
+//!   aktuelle Unix-Zeit in Millisekunden in einen geteilten Zaehler; bleibt
+//!   er laenger als erlaubt stehen, gilt die Komponente als ausgefallen
+//!
+//! Alle Pruefungen laufen parallel, eine haengende Pruefung kostet daher
+//! hoechstens das Zeitlimit.
+
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use crate::health::HealthStatus;
+
+/// Standard-Zeitlimit einer einzelnen Pruefung
+///
+/// Deutlich unter dem Anfrage-Zeitlimit des Observability-Servers, damit
+/// `/health` auch bei haengenden Pruefungen noch antwortet.
+pub const PRUEFUNG_ZEITLIMIT: Duration = Duration::from_secs(2);
+
+/// Ergebnis einer Komponenten-Pruefung
+#[derive(Debug, Clone, PartialEq)]
+pub struct Befund {
+    pub status: HealthStatus,
+    pub detail: Option<String>,
+}
+
+impl Befund {
+    pub fn gesund() -> Self {
+        Self {
+            status: HealthStatus::Healthy,
+            detail: None,
+        }
+    }
+
+    pub fn eingeschraenkt(detail: impl Into<String>) -> Self {
+        Self {
+            status: HealthStatus::Degraded,
+            detail: Some(detail.into()),
+        }
+    }
+
+    pub fn ausgefallen(detail: impl Into<String>) -> Self {
+        Self {
+            status: HealthStatus::Unhealthy,
+            detail: Some(detail.into()),
+        }
+    }
+
+    /// Ergaenzt einen Hinweis, ohne den Status zu aendern
+    pub fn mit_detail(mut self, detail: impl Into<String>) -> Self {
+        self.detail = Some(detail.into());
+        self
+    }
+}
+
+/// Zustand einer Komponente in der Antwort von `/health`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct KomponentenStatus {
+    pub status: HealthStatus,
+    /// Dauer der Pruefung (bei Herzschlaegen `0`)
+    pub latency_ms: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+type AsyncPruefung = Arc<dyn Fn() -> Pin<Box<dyn Future<Output = Befund> + Send>> + Send + Sync>;
+
+#[derive(Clone)]
+enum Komponente {
+    Pruefung(AsyncPruefung),
+    Herzschlag {
+        zaehler: Arc<AtomicU64>,
+        max_alter: Duration,
+    },
+}
+
+/// Register der Komponenten, deren Zustand `/health` meldet
+#[derive(Clone)]
+pub struct HealthRegistry {
+    komponenten: Arc<RwLock<Vec<(String, Komponente)>>>,
+    zeitlimit: Duration,
+}
+
+impl Default for HealthRegistry {
+    fn default() -> Self {
+        Self::neu()
+    }
+}
+
+impl HealthRegistry {
+    pub fn neu() -> Self {
+        Self {
+            komponenten: Arc::new(RwLock::new(Vec::new())),
+            zeitlimit: PRUEFUNG_ZEITLIMIT,
+        }
+    }
+
+    /// Setzt das Zeitlimit fuer asynchrone Pruefungen
+    pub fn mit_zeitlimit(mut self, zeitlimit: Duration) -> Self {
+        self.zeitlimit = zeitlimit;
+        self
+    }
+
+    /// Registriert eine asynchrone Pruefung
+    ///
+    /// Ueberschreitet sie das Zeitlimit oder bricht sie mit einem Panic ab,
+    /// gilt die Komponente als ausgefallen. Ein bereits registrierter Name
+    /// wird ersetzt.
+    pub fn pruefung_registrieren<F, Fut>(&self, name: impl Into<String>, pruefung: F)
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Befund> + Send + 'static,
+    {
+        let pruefung: AsyncPruefung = Arc::new(move || Box::pin(pruefung()));
+        self.einfuegen(name.into(), Komponente::Pruefung(pruefung));
+    }
+
+    /// Registriert einen Herzschlag und gibt den Zaehler fuer das Subsystem zurueck
+    ///
+    /// Das Subsystem schreibt die aktuelle Unix-Zeit in Millisekunden in den
+    /// Zaehler (siehe [`herzschlag_jetzt`]). Bis zum ersten Schlag und wenn
+    /// der letzte laenger als `max_alter` zurueckliegt, gilt die Komponente
+    /// als ausgefallen.
+    pub fn herzschlag_registrieren(
+        &self,
+        name: impl Into<String>,
+        max_alter: Duration,
+    ) -> Arc<AtomicU64> {
+        let zaehler = Arc::new(AtomicU64::new(0));
+        self.einfuegen(
+            name.into(),
+            Komponente::Herzschlag {
+                zaehler: Arc::clone(&zaehler),
+                max_alter,
+            },
+        );
+        zaehler
+    }
+
+    pub fn ist_leer(&self) -> bool {
+        self.komponenten
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .is_empty()
+    }
+
+    /// Prueft alle Komponenten parallel
+    pub async fn auswerten(&self) -> BTreeMap<String, KomponentenStatus> {
+        // Kopie, damit waehrend der Pruefungen keine Sperre gehalten wird
+        let komponenten: Vec<_> = self
+            .komponenten
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone();
+
+        let mut laufend = Vec::new();
+        let mut ergebnis = BTreeMap::new();
+        for (name, komponente) in komponenten {
+            match komponente {
+                Komponente::Pruefung(pruefung) => {
+                    let zeitlimit = self.zeitlimit;
+                    let task = tokio::spawn(async move {
+                        let start = Instant::now();
+                        let befund = tokio::time::timeout(zeitlimit, pruefung())
+                            .await
+                            .unwrap_or_else(|_| {
+                                Befund::ausgefallen(format!(
+                                    "Zeitlimit von {} ms ueberschritten",
+                                    zeitlimit.as_millis()
+                                ))
+                            });
+                        (befund, start.elapsed())
+                    });
+                    laufend.push((name, task));
+                }
+                Komponente::Herzschlag { zaehler, max_alter } => {
+                    let befund = herzschlag_bewerten(zaehler.load(Ordering::Relaxed), max_alter);
+                    ergebnis.insert(name, status_aus(befund, Duration::ZERO));
+                }
+            }
+        }
+
+        for (name, task) in laufend {
+            let (befund, dauer) = task.await.unwrap_or_else(|e| {
+                tracing::error!(komponente = %name, fehler = %e, "Health-Pruefung abgebrochen");
+                (Befund::ausgefallen("Pruefung abgebrochen"), Duration::ZERO)
+            });
+            if befund.status != HealthStatus::Healthy {
+                tracing::debug!(komponente = %name, ?befund, "Komponente nicht gesund");
+            }
+            ergebnis.insert(name, status_aus(befund, dauer));
+        }
+        ergebnis
+    }
+
+    fn einfuegen(&self, name: String, komponente: Komponente) {
+        let mut komponenten = self.komponenten.write().unwrap_or_else(|e| e.into_inner());
+        komponenten.retain(|(vorhanden, _)| *vorhanden != name);
+        komponenten.push((name, komponente));
+    }
+}
+
+/// Aktuelle Unix-Zeit in Millisekunden, wie sie Herzschlag-Zaehler erwarten
+pub fn herzschlag_jetzt() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+fn herzschlag_bewerten(letzter_ms: u64, max_alter: Duration) -> Befund {
+    if letzter_ms == 0 {
+        return Befund::ausgefallen("noch kein Herzschlag");
+    }
+    let alter_ms = herzschlag_jetzt().saturating_sub(letzter_ms);
+    if alter_ms > max_alter.as_millis() as u64 {
+        Befund::ausgefallen(format!("letzter Herzschlag vor {alter_ms} ms"))
+    } else {
+        Befund::gesund()
+    }
+}
+
+fn status_aus(befund: Befund, dauer: Duration) -> KomponentenStatus {
+    KomponentenStatus {
+        status: befund.status,
+        latency_ms: dauer.as_millis() as u64,
+        detail: befund.detail,
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn fehlschlagende_pruefung_erscheint_mit_detail() {
+        let registry = HealthRegistry::neu();
+        registry.pruefung_registrieren("database", || async {
+            Befund::ausgefallen("SELECT 1 fehlgeschlagen")
+        });
+        registry.pruefung_registrieren("disk", || async {
+            Befund::gesund().mit_detail("42.0 % belegt")
+        });
+
+        let ergebnis = registry.auswerten().await;
+        assert_eq!(ergebnis.len(), 2);
+        assert_eq!(ergebnis["database"].status, HealthStatus::Unhealthy);
+        assert_eq!(
+            ergebnis["database"].detail.as_deref(),
+            Some("SELECT 1 fehlgeschlagen")
+        );
+        assert_eq!(ergebnis["disk"].status, HealthStatus::Healthy);
+        assert_eq!(ergebnis["disk"].detail.as_deref(), Some("42.0 % belegt"));
+    }
+
+    #[tokio::test]
+    async fn haengende_und_panische_pruefungen_fallen_aus() {
+        let registry = HealthRegistry::neu().mit_zeitlimit(Duration::from_millis(50));
+        registry.pruefung_registrieren("haengt", || async {
+            tokio::time::sleep(Duration::from_secs(60)).await;
+            Befund::gesund()
+        });
+        registry.pruefung_registrieren("panik", || async {
+            panic!("Pruefung kaputt");
+        });
+
+        let start = Instant::now();
+        let ergebnis = registry.auswerten().await;
+        assert!(start.elapsed() < Duration::from_secs(5));
+        assert_eq!(ergebnis["haengt"].status, HealthStatus::Unhealthy);
+        assert!(ergebnis["haengt"].latency_ms >= 50);
+        assert!(ergebnis["haengt"]
+            .detail
+            .as_deref()
+            .unwrap()
+            .contains("Zeitlimit"));
+        assert_eq!(ergebnis["panik"].status, HealthStatus::Unhealthy);
+    }
+
+    #[tokio::test]
+    async fn herzschlag_veraltet() {
+        let registry = HealthRegistry::neu();
+        let zaehler = registry.herzschlag_registrieren("voice", Duration::from_secs(5));
+        let ergebnis = registry.auswerten().await;
+        assert_eq!(ergebnis["voice"].status, HealthStatus::Unhealthy);
+        assert_eq!(
+            ergebnis["voice"].detail.as_deref(),
+            Some("noch kein Herzschlag")
+        );
+
+        zaehler.store(herzschlag_jetzt(), Ordering::Relaxed);
+        assert_eq!(
+            registry.auswerten().await["voice"].status,
+            HealthStatus::Healthy
+        );
+
+        zaehler.store(herzschlag_jetzt() - 10_000, Ordering::Relaxed);
+        let ergebnis = registry.auswerten().await;
+        assert_eq!(ergebnis["voice"].status, HealthStatus::Unhealthy);
+        assert!(ergebnis["voice"]
+            .detail
+            .as_deref()
+            .unwrap()
+            .starts_with("letzter Herzschlag vor"));
+    }
+
+    #[tokio::test]
+    async fn gleicher_name_ersetzt_die_pruefung() {
+        let registry = HealthRegistry::neu();
+        assert!(registry.ist_leer());
+        registry.pruefung_registrieren("database", || async { Befund::ausgefallen("alt") });
+        registry.pruefung_registrieren("database", || async { Befund::gesund() });
+
+        let ergebnis = registry.auswerten().await;
+        assert_eq!(ergebnis.len(), 1);
+        assert_eq!(ergebnis["database"].status, HealthStatus::Healthy);
+    }
+}
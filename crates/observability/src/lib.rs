@@ -2,36 +2,35 @@
 //!
 //! Observability-Crate fuer Speakeasy:
 //! - Prometheus-kompatible Metriken (`/metrics`)
-//! - Health-Check-Endpunkt (`/health`)
+//! - Health-Check-Endpunkt (`/health`) mit Zustand je Komponente und
+//!   Startbereitschaft (`/health/ready`, `/ready`)
 //! - Structured JSON Logging via tracing-subscriber
 //! - Request-Timing Middleware
+//! - Eingebaute Alarmregeln fuer Betreiber
+//!
+//! Ohne das Feature `metriken` entfallen Prometheus, der Observability-Server
+//! und die Datentraeger-Messung; Health-Zustand, Logging und die Alarm-Typen
+//! bleiben verfuegbar.
 
+pub mod alarm;
 pub mod health;
+pub mod komponenten;
 pub mod logging;
+#[cfg(feature = "metriken")]
 pub mod metrics;
 pub mod middleware;
+#[cfg(feature = "metriken")]
+pub mod server;
 
-pub use health::{health_router, HealthResponse, HealthStatus};
+pub use alarm::{AlarmAuswerter, AlarmMeldung, AlarmRegel, AlarmStatus, FlapGrenzen, Messwerte};
+pub use health::{
+    health_router, HealthResponse, HealthState, HealthStatus, ReadyResponse, ReadyStatus,
+    StartPhase,
+};
+pub use komponenten::{herzschlag_jetzt, Befund, HealthRegistry, KomponentenStatus};
 pub use logging::logging_initialisieren;
+#[cfg(feature = "metriken")]
 pub use metrics::{metrics_router, SpeakeasyMetrics};
 pub use middleware::request_timing_layer;
-
-use anyhow::Result;
-use std::net::SocketAddr;
-
-/// Startet den Observability-HTTP-Server (Metriken + Health)
-///
-/// Endpunkte:
-/// - `GET /metrics` – Prometheus scrape format
-/// - `GET /health`  – Health-Check JSON
-pub async fn observability_server_starten(bind_addr: SocketAddr) -> Result<()> {
-    use axum::Router;
-
-    let app = Router::new().merge(metrics_router()).merge(health_router());
-
-    let listener = tokio::net::TcpListener::bind(bind_addr).await?;
-    tracing::info!(addr = %bind_addr, "Observability-Server gestartet");
-
-    axum::serve(listener, app).await?;
-    Ok(())
-}
+#[cfg(feature = "metriken")]
+pub use server::{observability_router, observability_server_starten};
@@ -8,16 +8,37 @@
 //! - `speakeasy_voice_rtt_seconds` – Histogram: Round-Trip-Time
 //! - `speakeasy_voice_jitter_seconds` – Histogram: Jitter
 //! - `speakeasy_voice_bitrate_bps` – Gauge: Aktuelle Bitrate
+//! - `speakeasy_voice_socket_rx_drops_total` – Counter: Vom Kernel verworfene Voice-Datagramme
+//! - `speakeasy_voice_socket_rx_queue_bytes` – Gauge: Belegung des Empfangspuffers
+//! - `speakeasy_voice_socket_rx_buffer_bytes` – Gauge: Effektive Groesse des Empfangspuffers
+//! - `speakeasy_voice_stale_drops_total` – Counter: Wegen Verspaetung verworfene Voice-Pakete
+//! - `speakeasy_voice_listen_only_drops_total` – Counter: Verworfene Pakete von Nur-Zuhoerern
+//! - `speakeasy_voice_emergency_mute_drops_total` – Counter: Verworfene Pakete in notfall-stummen Kanaelen
+//! - `speakeasy_voice_icmp_unreachable_total` – Counter: ICMP-Rueckmeldungen am Voice-Socket
+//! - `speakeasy_voice_unknown_source_drops_total` – Counter: Verworfene Pakete unbekannter Absender
+//! - `speakeasy_voice_hello_rejected_total` – Counter: Abgelehnte Hellos (ungueltiger HMAC)
+//! - `speakeasy_voice_packets_received_total` – Counter: Im Jitter Buffer empfangene Pakete (ssrc)
+//! - `speakeasy_voice_packets_lost_total` – Counter: Im Jitter Buffer verlorene Pakete (ssrc)
+//! - `speakeasy_voice_packets_duplicate_total` – Counter: Verworfene Duplikate (ssrc)
+//! - `speakeasy_voice_jitter_ticks` – Gauge: Gemessener Jitter in RTP-Ticks (ssrc)
+//! - `speakeasy_voice_buffer_fill` – Gauge: Fuellstand des Jitter Buffers in Paketen (ssrc)
+//! - `speakeasy_voice_channel_mos` – Gauge: Mittlere geschaetzte Sprachqualitaet (channel)
+//! - `speakeasy_signaling_requests_in_flight` – Gauge: Laufende Signaling-Anfragen
+//! - `speakeasy_signaling_db_requests_in_flight` – Gauge: Davon mit belegtem Datenbank-Platz
+//! - `speakeasy_signaling_requests_rejected_total` – Counter: Wegen Gleichzeitigkeitsgrenzen abgelehnte Anfragen
 //! - `speakeasy_cpu_usage_percent` – Gauge: CPU-Auslastung
 //! - `speakeasy_memory_usage_bytes` – Gauge: Speicherverbrauch
+//! - `speakeasy_unclean_shutdowns_total` – Counter: Starts nach einem unsauberen Shutdown
 //! - `speakeasy_http_requests_total` – Counter: HTTP-Anfragen (method, path, status)
 //! - `speakeasy_http_request_duration_seconds` – Histogram: HTTP-Antwortzeit
+//! - `speakeasy_http_panics_total` – Counter: Abgefangene Panics in HTTP-Handlern
 
 use anyhow::Result;
 use axum::{response::IntoResponse, routing::get, Router};
+use prometheus::core::Collector;
 use prometheus::{
-    Counter, Encoder, Gauge, Histogram, HistogramOpts, HistogramVec, IntCounterVec, Opts, Registry,
-    TextEncoder,
+    Counter, Encoder, Gauge, GaugeVec, Histogram, HistogramOpts, HistogramVec, IntCounter,
+    IntCounterVec, IntGaugeVec, Opts, Registry, TextEncoder,
 };
 use std::sync::Arc;
 
@@ -34,14 +55,36 @@ pub struct SpeakeasyMetrics {
     pub voice_rtt_seconds: Histogram,
     pub voice_jitter_seconds: Histogram,
     pub voice_bitrate_bps: Gauge,
+    pub voice_socket_rx_drops_total: IntCounter,
+    pub voice_socket_rx_queue_bytes: Gauge,
+    pub voice_socket_rx_buffer_bytes: Gauge,
+    pub voice_stale_drops_total: IntCounter,
+    pub voice_listen_only_drops_total: IntCounter,
+    pub voice_emergency_mute_drops_total: IntCounter,
+    pub voice_icmp_unreachable_total: IntCounter,
+    pub voice_unknown_source_drops_total: IntCounter,
+    pub voice_hello_rejected_total: IntCounter,
+    pub voice_packets_received_total: IntCounterVec,
+    pub voice_packets_lost_total: IntCounterVec,
+    pub voice_packets_duplicate_total: IntCounterVec,
+    pub voice_jitter_ticks: IntGaugeVec,
+    pub voice_buffer_fill: IntGaugeVec,
+    pub voice_channel_mos: GaugeVec,
+
+    // Signaling-Metriken
+    pub signaling_requests_in_flight: Gauge,
+    pub signaling_db_requests_in_flight: Gauge,
+    pub signaling_requests_rejected_total: IntCounter,
 
     // System-Metriken
     pub cpu_usage_percent: Gauge,
     pub memory_usage_bytes: Gauge,
+    pub unclean_shutdowns_total: IntCounter,
 
     // HTTP-Metriken
     pub http_requests_total: IntCounterVec,
     pub http_request_duration_seconds: HistogramVec,
+    pub http_panics_total: IntCounter,
 }
 
 impl SpeakeasyMetrics {
@@ -95,6 +138,133 @@ impl SpeakeasyMetrics {
         ))?;
         registry.register(Box::new(voice_bitrate_bps.clone()))?;
 
+        let voice_socket_rx_drops_total = IntCounter::with_opts(Opts::new(
+            "speakeasy_voice_socket_rx_drops_total",
+            "Vom Kernel verworfene Voice-Datagramme (Empfangspuffer voll)",
+        ))?;
+        registry.register(Box::new(voice_socket_rx_drops_total.clone()))?;
+
+        let voice_socket_rx_queue_bytes = Gauge::with_opts(Opts::new(
+            "speakeasy_voice_socket_rx_queue_bytes",
+            "Belegte Bytes im Empfangspuffer des Voice-Sockets",
+        ))?;
+        registry.register(Box::new(voice_socket_rx_queue_bytes.clone()))?;
+
+        let voice_socket_rx_buffer_bytes = Gauge::with_opts(Opts::new(
+            "speakeasy_voice_socket_rx_buffer_bytes",
+            "Effektive Groesse des Empfangspuffers des Voice-Sockets",
+        ))?;
+        registry.register(Box::new(voice_socket_rx_buffer_bytes.clone()))?;
+
+        let voice_stale_drops_total = IntCounter::with_opts(Opts::new(
+            "speakeasy_voice_stale_drops_total",
+            "Wegen Verspaetung nicht weitergeleitete Voice-Pakete (Paket-TTL)",
+        ))?;
+        registry.register(Box::new(voice_stale_drops_total.clone()))?;
+
+        let voice_listen_only_drops_total = IntCounter::with_opts(Opts::new(
+            "speakeasy_voice_listen_only_drops_total",
+            "Verworfene Voice-Pakete von Clients im Nur-Zuhoeren-Modus",
+        ))?;
+        registry.register(Box::new(voice_listen_only_drops_total.clone()))?;
+
+        let voice_emergency_mute_drops_total = IntCounter::with_opts(Opts::new(
+            "speakeasy_voice_emergency_mute_drops_total",
+            "Verworfene Voice-Pakete in notfall-stumm geschalteten Kanaelen",
+        ))?;
+        registry.register(Box::new(voice_emergency_mute_drops_total.clone()))?;
+
+        let voice_icmp_unreachable_total = IntCounter::with_opts(Opts::new(
+            "speakeasy_voice_icmp_unreachable_total",
+            "Am Voice-Socket gemeldete ICMP-Rueckmeldungen (Ziel unerreichbar)",
+        ))?;
+        registry.register(Box::new(voice_icmp_unreachable_total.clone()))?;
+
+        let voice_unknown_source_drops_total = IntCounter::with_opts(Opts::new(
+            "speakeasy_voice_unknown_source_drops_total",
+            "Verworfene Voice-Pakete von unbekannten SSRCs oder unbestaetigten Endpunkten",
+        ))?;
+        registry.register(Box::new(voice_unknown_source_drops_total.clone()))?;
+
+        let voice_hello_rejected_total = IntCounter::with_opts(Opts::new(
+            "speakeasy_voice_hello_rejected_total",
+            "Abgelehnte Voice-Hellos (ungueltiger HMAC)",
+        ))?;
+        registry.register(Box::new(voice_hello_rejected_total.clone()))?;
+
+        let voice_packets_received_total = IntCounterVec::new(
+            Opts::new(
+                "speakeasy_voice_packets_received_total",
+                "Im Jitter Buffer empfangene Voice-Pakete pro Absender",
+            ),
+            &["ssrc"],
+        )?;
+        registry.register(Box::new(voice_packets_received_total.clone()))?;
+
+        let voice_packets_lost_total = IntCounterVec::new(
+            Opts::new(
+                "speakeasy_voice_packets_lost_total",
+                "Im Jitter Buffer verlorene Voice-Pakete pro Absender",
+            ),
+            &["ssrc"],
+        )?;
+        registry.register(Box::new(voice_packets_lost_total.clone()))?;
+
+        let voice_packets_duplicate_total = IntCounterVec::new(
+            Opts::new(
+                "speakeasy_voice_packets_duplicate_total",
+                "Im Jitter Buffer verworfene doppelte Voice-Pakete pro Absender",
+            ),
+            &["ssrc"],
+        )?;
+        registry.register(Box::new(voice_packets_duplicate_total.clone()))?;
+
+        let voice_jitter_ticks = IntGaugeVec::new(
+            Opts::new(
+                "speakeasy_voice_jitter_ticks",
+                "Gemessener Jitter pro Absender in RTP-Ticks (48 kHz)",
+            ),
+            &["ssrc"],
+        )?;
+        registry.register(Box::new(voice_jitter_ticks.clone()))?;
+
+        let voice_buffer_fill = IntGaugeVec::new(
+            Opts::new(
+                "speakeasy_voice_buffer_fill",
+                "Fuellstand des Jitter Buffers pro Absender in Paketen",
+            ),
+            &["ssrc"],
+        )?;
+        registry.register(Box::new(voice_buffer_fill.clone()))?;
+
+        let voice_channel_mos = GaugeVec::new(
+            Opts::new(
+                "speakeasy_voice_channel_mos",
+                "Mittlere geschaetzte Sprachqualitaet (MOS 1-4.5) pro Kanal",
+            ),
+            &["channel"],
+        )?;
+        registry.register(Box::new(voice_channel_mos.clone()))?;
+
+        // --- Signaling-Metriken ---
+        let signaling_requests_in_flight = Gauge::with_opts(Opts::new(
+            "speakeasy_signaling_requests_in_flight",
+            "Aktuell laufende Signaling-Anfragen",
+        ))?;
+        registry.register(Box::new(signaling_requests_in_flight.clone()))?;
+
+        let signaling_db_requests_in_flight = Gauge::with_opts(Opts::new(
+            "speakeasy_signaling_db_requests_in_flight",
+            "Laufende datenbanklastige Signaling-Anfragen",
+        ))?;
+        registry.register(Box::new(signaling_db_requests_in_flight.clone()))?;
+
+        let signaling_requests_rejected_total = IntCounter::with_opts(Opts::new(
+            "speakeasy_signaling_requests_rejected_total",
+            "Wegen Gleichzeitigkeitsgrenzen abgelehnte Signaling-Anfragen",
+        ))?;
+        registry.register(Box::new(signaling_requests_rejected_total.clone()))?;
+
         // --- System-Metriken ---
         let cpu_usage_percent = Gauge::with_opts(Opts::new(
             "speakeasy_cpu_usage_percent",
@@ -108,6 +278,12 @@ impl SpeakeasyMetrics {
         ))?;
         registry.register(Box::new(memory_usage_bytes.clone()))?;
 
+        let unclean_shutdowns_total = IntCounter::with_opts(Opts::new(
+            "speakeasy_unclean_shutdowns_total",
+            "Serverstarts nach einem unsauberen Shutdown (Absturz, kill -9)",
+        ))?;
+        registry.register(Box::new(unclean_shutdowns_total.clone()))?;
+
         // --- HTTP-Metriken ---
         let http_requests_total = IntCounterVec::new(
             Opts::new(
@@ -130,6 +306,12 @@ impl SpeakeasyMetrics {
         )?;
         registry.register(Box::new(http_request_duration_seconds.clone()))?;
 
+        let http_panics_total = IntCounter::with_opts(Opts::new(
+            "speakeasy_http_panics_total",
+            "Anzahl abgefangener Panics in HTTP-Handlern",
+        ))?;
+        registry.register(Box::new(http_panics_total.clone()))?;
+
         Ok(Self {
             registry: Arc::new(registry),
             connected_clients,
@@ -139,13 +321,77 @@ impl SpeakeasyMetrics {
             voice_rtt_seconds,
             voice_jitter_seconds,
             voice_bitrate_bps,
+            voice_socket_rx_drops_total,
+            voice_socket_rx_queue_bytes,
+            voice_socket_rx_buffer_bytes,
+            voice_stale_drops_total,
+            voice_listen_only_drops_total,
+            voice_emergency_mute_drops_total,
+            voice_icmp_unreachable_total,
+            voice_unknown_source_drops_total,
+            voice_hello_rejected_total,
+            voice_packets_received_total,
+            voice_packets_lost_total,
+            voice_packets_duplicate_total,
+            voice_jitter_ticks,
+            voice_buffer_fill,
+            voice_channel_mos,
+            signaling_requests_in_flight,
+            signaling_db_requests_in_flight,
+            signaling_requests_rejected_total,
             cpu_usage_percent,
             memory_usage_bytes,
+            unclean_shutdowns_total,
             http_requests_total,
             http_request_duration_seconds,
+            http_panics_total,
         })
     }
 
+    /// Entfernt alle Jitter-Buffer-Metriken einer abgemeldeten SSRC
+    pub fn ssrc_entfernen(&self, ssrc: &str) {
+        // Fehler heisst nur: fuer diese SSRC nie gesetzt
+        let _ = self
+            .voice_packets_received_total
+            .remove_label_values(&[ssrc]);
+        let _ = self.voice_packets_lost_total.remove_label_values(&[ssrc]);
+        let _ = self
+            .voice_packets_duplicate_total
+            .remove_label_values(&[ssrc]);
+        let _ = self.voice_jitter_ticks.remove_label_values(&[ssrc]);
+        let _ = self.voice_buffer_fill.remove_label_values(&[ssrc]);
+    }
+
+    /// Ersetzt die Sprachqualitaet aller Kanaele
+    ///
+    /// Kanaele ohne Bewertung verschwinden aus dem Export, statt mit einem
+    /// veralteten Wert stehen zu bleiben.
+    pub fn kanal_mos_setzen<'a>(&self, werte: impl IntoIterator<Item = (&'a str, f64)>) {
+        self.voice_channel_mos.reset();
+        for (kanal, mos) in werte {
+            self.voice_channel_mos.with_label_values(&[kanal]).set(mos);
+        }
+    }
+
+    /// Empfangene und verlorene Voice-Pakete, summiert ueber alle SSRCs
+    ///
+    /// Abgemeldete SSRCs fallen aus der Summe; Differenzen zwischen zwei
+    /// Abfragen koennen dadurch kleiner ausfallen als tatsaechlich.
+    pub fn voice_pakete_summe(&self) -> (u64, u64) {
+        let summe = |zaehler: &IntCounterVec| -> u64 {
+            zaehler
+                .collect()
+                .iter()
+                .flat_map(|familie| familie.get_metric())
+                .map(|metrik| metrik.get_counter().get_value() as u64)
+                .sum()
+        };
+        (
+            summe(&self.voice_packets_received_total),
+            summe(&self.voice_packets_lost_total),
+        )
+    }
+
     /// Exportiert alle Metriken im Prometheus-Textformat
     pub fn exportieren(&self) -> Result<String> {
         let encoder = TextEncoder::new();
@@ -156,16 +402,19 @@ impl SpeakeasyMetrics {
     }
 }
 
-/// Axum-Router fuer den `/metrics`-Endpunkt
-pub fn metrics_router() -> Router {
+/// Prozessweite Metriken, die unter `/metrics` exportiert werden
+pub fn globale_metriken() -> &'static SpeakeasyMetrics {
     use std::sync::OnceLock;
     static METRIKEN: OnceLock<SpeakeasyMetrics> = OnceLock::new();
-    let metriken = METRIKEN
-        .get_or_init(|| SpeakeasyMetrics::neu().expect("Metriken-Initialisierung fehlgeschlagen"));
+    METRIKEN
+        .get_or_init(|| SpeakeasyMetrics::neu().expect("Metriken-Initialisierung fehlgeschlagen"))
+}
 
+/// Axum-Router fuer den `/metrics`-Endpunkt
+pub fn metrics_router() -> Router {
     Router::new()
         .route("/metrics", get(metrics_handler))
-        .with_state(metriken.clone())
+        .with_state(globale_metriken().clone())
 }
 
 async fn metrics_handler(
@@ -236,6 +485,56 @@ mod tests {
         assert_eq!(wert, 1);
     }
 
+    #[test]
+    fn ssrc_metriken_werden_entfernt() {
+        let metriken = SpeakeasyMetrics::neu().unwrap();
+        metriken
+            .voice_packets_lost_total
+            .with_label_values(&["4660"])
+            .inc_by(3);
+        metriken
+            .voice_buffer_fill
+            .with_label_values(&["4660"])
+            .set(4);
+        assert!(metriken.exportieren().unwrap().contains("ssrc=\"4660\""));
+
+        metriken.ssrc_entfernen("4660");
+        metriken.ssrc_entfernen("4660");
+        assert!(!metriken.exportieren().unwrap().contains("ssrc=\"4660\""));
+    }
+
+    #[test]
+    fn kanal_mos_ersetzt_alte_werte() {
+        let metriken = SpeakeasyMetrics::neu().unwrap();
+        metriken.kanal_mos_setzen([("a", 4.2), ("b", 3.1)]);
+        assert_eq!(
+            metriken.voice_channel_mos.with_label_values(&["b"]).get(),
+            3.1
+        );
+
+        metriken.kanal_mos_setzen([("b", 3.5)]);
+        let text = metriken.exportieren().unwrap();
+        assert!(!text.contains("channel=\"a\""));
+        assert!(text.contains("speakeasy_voice_channel_mos{channel=\"b\"} 3.5"));
+    }
+
+    #[test]
+    fn voice_pakete_ueber_alle_ssrcs() {
+        let metriken = SpeakeasyMetrics::neu().unwrap();
+        assert_eq!(metriken.voice_pakete_summe(), (0, 0));
+        for (ssrc, empfangen, verloren) in [("1", 90, 10), ("2", 50, 0)] {
+            metriken
+                .voice_packets_received_total
+                .with_label_values(&[ssrc])
+                .inc_by(empfangen);
+            metriken
+                .voice_packets_lost_total
+                .with_label_values(&[ssrc])
+                .inc_by(verloren);
+        }
+        assert_eq!(metriken.voice_pakete_summe(), (140, 10));
+    }
+
     #[test]
     fn metriken_export_prometheus_format() {
         let metriken = SpeakeasyMetrics::neu().unwrap();
@@ -263,6 +562,20 @@ mod tests {
             .http_request_duration_seconds
             .with_label_values(&["GET", "/test"])
             .observe(0.01);
+        metriken
+            .voice_packets_received_total
+            .with_label_values(&["1"])
+            .inc();
+        metriken
+            .voice_packets_lost_total
+            .with_label_values(&["1"])
+            .inc();
+        metriken
+            .voice_packets_duplicate_total
+            .with_label_values(&["1"])
+            .inc();
+        metriken.voice_jitter_ticks.with_label_values(&["1"]).set(1);
+        metriken.voice_buffer_fill.with_label_values(&["1"]).set(1);
 
         let families = metriken.registry.gather();
         let namen: Vec<&str> = families.iter().map(|f| f.get_name()).collect();
@@ -274,7 +587,25 @@ mod tests {
         assert!(namen.contains(&"speakeasy_voice_rtt_seconds"));
         assert!(namen.contains(&"speakeasy_voice_jitter_seconds"));
         assert!(namen.contains(&"speakeasy_voice_bitrate_bps"));
+        assert!(namen.contains(&"speakeasy_voice_socket_rx_drops_total"));
+        assert!(namen.contains(&"speakeasy_voice_socket_rx_queue_bytes"));
+        assert!(namen.contains(&"speakeasy_voice_socket_rx_buffer_bytes"));
+        assert!(namen.contains(&"speakeasy_voice_stale_drops_total"));
+        assert!(namen.contains(&"speakeasy_voice_listen_only_drops_total"));
+        assert!(namen.contains(&"speakeasy_voice_emergency_mute_drops_total"));
+        assert!(namen.contains(&"speakeasy_voice_icmp_unreachable_total"));
+        assert!(namen.contains(&"speakeasy_voice_unknown_source_drops_total"));
+        assert!(namen.contains(&"speakeasy_voice_hello_rejected_total"));
+        assert!(namen.contains(&"speakeasy_voice_packets_received_total"));
+        assert!(namen.contains(&"speakeasy_voice_packets_lost_total"));
+        assert!(namen.contains(&"speakeasy_voice_packets_duplicate_total"));
+        assert!(namen.contains(&"speakeasy_voice_jitter_ticks"));
+        assert!(namen.contains(&"speakeasy_voice_buffer_fill"));
+        assert!(namen.contains(&"speakeasy_signaling_requests_in_flight"));
+        assert!(namen.contains(&"speakeasy_signaling_db_requests_in_flight"));
+        assert!(namen.contains(&"speakeasy_signaling_requests_rejected_total"));
         assert!(namen.contains(&"speakeasy_cpu_usage_percent"));
+        assert!(namen.contains(&"speakeasy_unclean_shutdowns_total"));
         assert!(namen.contains(&"speakeasy_memory_usage_bytes"));
         assert!(namen.contains(&"speakeasy_http_requests_total"));
         assert!(namen.contains(&"speakeasy_http_request_duration_seconds"));
@@ -0,0 +1,389 @@
+//! Observability-HTTP-Server (Metriken + Health)
+//!
+//! Der Router wird mit eigener Middleware abgesichert:
+//! - Request-Timing (Logging jeder Anfrage)
+//! - Panics in Handlern werden zu `500` und in `speakeasy_http_panics_total` gezaehlt
+//! - Zeitlimit und Parallelitaetsgrenze pro Route, damit sich bei einem
+//!   haengenden Health-Check keine Anfragen aufstauen
+//!
+//! Beendet sich `axum::serve` mit einem Fehler, wird der Listener mit
+//! exponentiellem Backoff neu gestartet, bis das Shutdown-Signal kommt.
+
+use std::any::Any;
+use std::net::SocketAddr;
+use std::time::{Duration, Instant};
+
+use axum::{
+    body::Body,
+    error_handling::HandleErrorLayer,
+    http::{Response, StatusCode},
+    response::IntoResponse,
+    BoxError, Router,
+};
+use tokio::sync::watch;
+use tower::ServiceBuilder;
+use tower_http::{catch_panic::CatchPanicLayer, timeout::TimeoutLayer};
+
+use crate::health::{health_router_mit_zustand, HealthState};
+use crate::metrics::{globale_metriken, metrics_router};
+use crate::middleware::request_timing_layer;
+
+/// Maximale Anzahl gleichzeitig bearbeiteter Anfragen pro Route
+pub const MAX_GLEICHZEITIGE_ANFRAGEN: usize = 16;
+/// Zeitlimit pro Anfrage
+pub const ANFRAGE_ZEITLIMIT: Duration = Duration::from_secs(5);
+
+/// Erste Wartezeit vor einem Neustart des Listeners
+const NEUSTART_BACKOFF_START: Duration = Duration::from_millis(500);
+/// Obergrenze der Wartezeit; laeuft der Server so lange stabil, beginnt der
+/// Backoff beim naechsten Fehler wieder von vorn
+const NEUSTART_BACKOFF_MAX: Duration = Duration::from_secs(30);
+
+/// Erstellt den Observability-Router mit Middleware
+///
+/// Endpunkte:
+/// - `GET /metrics` – Prometheus scrape format
+/// - `GET /health`  – Health-Check JSON mit Zustand je Komponente
+/// - `GET /health/ready`, `GET /ready` – Startbereitschaft JSON
+pub fn observability_router(health: HealthState) -> Router {
+    let panics = globale_metriken().http_panics_total.clone();
+
+    // axum wendet den Layer pro Route an: ein haengendes `/health`
+    // belegt daher nicht die Plaetze von `/metrics`
+    Router::new()
+        .merge(metrics_router())
+        .merge(health_router_mit_zustand(health))
+        .layer(
+            ServiceBuilder::new()
+                .layer(request_timing_layer())
+                .layer(CatchPanicLayer::custom(
+                    move |panik: Box<dyn Any + Send + 'static>| {
+                        panics.inc();
+                        panik_antwort(panik)
+                    },
+                ))
+                .layer(HandleErrorLayer::new(ueberlastet))
+                .load_shed()
+                .concurrency_limit(MAX_GLEICHZEITIGE_ANFRAGEN)
+                .layer(TimeoutLayer::new(ANFRAGE_ZEITLIMIT)),
+        )
+}
+
+/// Wandelt einen abgefangenen Panic in eine `500`-Antwort um
+fn panik_antwort(panik: Box<dyn Any + Send + 'static>) -> Response<Body> {
+    let meldung = panik
+        .downcast_ref::<String>()
+        .map(String::as_str)
+        .or_else(|| panik.downcast_ref::<&str>().copied())
+        .unwrap_or("unbekannt");
+    tracing::error!(panik = %meldung, "Panic in Observability-Handler abgefangen");
+
+    StatusCode::INTERNAL_SERVER_ERROR.into_response()
+}
+
+/// Antwort, wenn alle Plaetze einer Route belegt sind
+async fn ueberlastet(fehler: BoxError) -> StatusCode {
+    tracing::warn!(fehler = %fehler, "Observability-Anfrage abgewiesen");
+    StatusCode::SERVICE_UNAVAILABLE
+}
+
+/// Startet den Observability-HTTP-Server und haelt ihn bis zum Shutdown am Laufen
+///
+/// Schlaegt das Binden oder `axum::serve` fehl, wird nach einer Wartezeit
+/// neu gestartet. Kehrt erst zurueck, wenn `shutdown_rx` `true` meldet oder
+/// der Sender verworfen wird.
+pub async fn observability_server_starten(
+    bind_addr: SocketAddr,
+    health: HealthState,
+    mut shutdown_rx: watch::Receiver<bool>,
+) -> anyhow::Result<()> {
+    let app = observability_router(health);
+    let mut backoff = NEUSTART_BACKOFF_START;
+
+    loop {
+        if *shutdown_rx.borrow() {
+            return Ok(());
+        }
+
+        let gestartet = Instant::now();
+        let fehler = match tokio::net::TcpListener::bind(bind_addr).await {
+            Ok(listener) => {
+                tracing::info!(addr = %bind_addr, "Observability-Server gestartet");
+                let mut rx = shutdown_rx.clone();
+                let ergebnis = axum::serve(listener, app.clone())
+                    .with_graceful_shutdown(async move {
+                        let _ = rx.wait_for(|beenden| *beenden).await;
+                    })
+                    .await;
+                match ergebnis {
+                    // Regulaeres Ende nur durch das Shutdown-Signal
+                    Ok(()) => {
+                        tracing::info!("Observability-Server beendet");
+                        return Ok(());
+                    }
+                    Err(e) => e,
+                }
+            }
+            Err(e) => e,
+        };
+
+        if gestartet.elapsed() >= NEUSTART_BACKOFF_MAX {
+            backoff = NEUSTART_BACKOFF_START;
+        }
+        tracing::error!(
+            fehler = %fehler,
+            addr = %bind_addr,
+            neustart_in_ms = backoff.as_millis() as u64,
+            "Observability-Server ausgefallen, starte neu"
+        );
+
+        tokio::select! {
+            _ = tokio::time::sleep(backoff) => {}
+            _ = shutdown_rx.wait_for(|beenden| *beenden) => return Ok(()),
+        }
+        backoff = (backoff * 2).min(NEUSTART_BACKOFF_MAX);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::health::{HealthStatus, StartPhase};
+    use crate::komponenten::Befund;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpStream;
+
+    /// Einfacher HTTP/1.1-GET ueber eine neue Verbindung
+    async fn http_get(addr: SocketAddr, pfad: &str) -> String {
+        let mut versuche = 0;
+        let mut stream = loop {
+            match TcpStream::connect(addr).await {
+                Ok(stream) => break stream,
+                Err(_) if versuche < 50 => {
+                    versuche += 1;
+                    tokio::time::sleep(Duration::from_millis(20)).await;
+                }
+                Err(e) => panic!("Verbindung zu {addr} fehlgeschlagen: {e}"),
+            }
+        };
+        let anfrage = format!("GET {pfad} HTTP/1.1\r\nHost: test\r\nConnection: close\r\n\r\n");
+        stream.write_all(anfrage.as_bytes()).await.unwrap();
+
+        let mut antwort = String::new();
+        stream.read_to_string(&mut antwort).await.unwrap();
+        antwort
+    }
+
+    fn freie_adresse() -> SocketAddr {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        listener.local_addr().unwrap()
+    }
+
+    #[tokio::test]
+    async fn panik_im_health_check_wird_zu_500() {
+        let health = HealthState::neu();
+        health.pruefung_registrieren("kaputt", || -> HealthStatus {
+            panic!("Pruefung kaputt");
+        });
+        let panics_vorher = globale_metriken().http_panics_total.get();
+
+        let addr = freie_adresse();
+        let (shutdown_tx, shutdown_rx) = watch::channel(false);
+        let server = tokio::spawn(observability_server_starten(addr, health, shutdown_rx));
+
+        let antwort = http_get(addr, "/health").await;
+        assert!(antwort.starts_with("HTTP/1.1 500"), "{antwort}");
+        assert!(globale_metriken().http_panics_total.get() > panics_vorher);
+
+        // Metriken bleiben erreichbar, der Server-Task laeuft weiter
+        let antwort = http_get(addr, "/metrics").await;
+        assert!(antwort.starts_with("HTTP/1.1 200"), "{antwort}");
+        assert!(antwort.contains("speakeasy_http_panics_total"));
+        assert!(!server.is_finished());
+
+        let antwort = http_get(addr, "/health").await;
+        assert!(antwort.starts_with("HTTP/1.1 500"), "{antwort}");
+
+        shutdown_tx.send(true).unwrap();
+        let ergebnis = tokio::time::timeout(Duration::from_secs(5), server)
+            .await
+            .expect("Server muss auf Shutdown reagieren")
+            .unwrap();
+        assert!(ergebnis.is_ok());
+    }
+
+    #[tokio::test]
+    async fn ausgefallene_komponente_meldet_degraded() {
+        let health = HealthState::neu();
+        health
+            .komponenten()
+            .pruefung_registrieren("database", || async { Befund::gesund() });
+        health
+            .komponenten()
+            .pruefung_registrieren("disk", || async {
+                Befund::ausgefallen("Datentraeger voll")
+            });
+        let addr = freie_adresse();
+        let (shutdown_tx, shutdown_rx) = watch::channel(false);
+        let server = tokio::spawn(observability_server_starten(
+            addr,
+            health.clone(),
+            shutdown_rx,
+        ));
+
+        let antwort = http_get(addr, "/health").await;
+        assert!(antwort.starts_with("HTTP/1.1 503"), "{antwort}");
+        assert!(antwort.contains("\"status\":\"degraded\""), "{antwort}");
+        assert!(
+            antwort.contains("\"database\":{\"status\":\"healthy\""),
+            "{antwort}"
+        );
+        assert!(
+            antwort.contains("\"detail\":\"Datentraeger voll\""),
+            "{antwort}"
+        );
+
+        // Bereitschaft haengt nur am Start, nicht an den Komponenten
+        health.phase_setzen(StartPhase::Abgeschlossen);
+        let antwort = http_get(addr, "/ready").await;
+        assert!(antwort.starts_with("HTTP/1.1 200"), "{antwort}");
+        assert!(antwort.contains("\"status\":\"ok\""), "{antwort}");
+
+        health
+            .komponenten()
+            .pruefung_registrieren("disk", || async { Befund::gesund() });
+        let antwort = http_get(addr, "/health").await;
+        assert!(antwort.starts_with("HTTP/1.1 200"), "{antwort}");
+        assert!(antwort.contains("\"status\":\"healthy\""), "{antwort}");
+
+        shutdown_tx.send(true).unwrap();
+        assert!(server.await.unwrap().is_ok());
+    }
+
+    #[tokio::test]
+    async fn belegte_adresse_wird_erneut_versucht() {
+        // Adresse zunaechst belegt: der Server wartet und versucht es erneut
+        let blockade = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = blockade.local_addr().unwrap();
+        let (shutdown_tx, shutdown_rx) = watch::channel(false);
+        let server = tokio::spawn(observability_server_starten(
+            addr,
+            HealthState::neu(),
+            shutdown_rx,
+        ));
+
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert!(!server.is_finished());
+        drop(blockade);
+
+        let antwort = http_get(addr, "/health").await;
+        assert!(antwort.starts_with("HTTP/1.1 200"), "{antwort}");
+
+        shutdown_tx.send(true).unwrap();
+        assert!(server.await.unwrap().is_ok());
+    }
+
+    #[tokio::test]
+    async fn startphasen_erscheinen_auf_health_ready() {
+        let health = HealthState::neu();
+        health.subsystem_erwarten("voice");
+        let addr = freie_adresse();
+        let (shutdown_tx, shutdown_rx) = watch::channel(false);
+        let server = tokio::spawn(observability_server_starten(
+            addr,
+            health.clone(),
+            shutdown_rx,
+        ));
+
+        let antwort = http_get(addr, "/health/ready").await;
+        assert!(antwort.starts_with("HTTP/1.1 503"), "{antwort}");
+        assert!(antwort.contains("\"detail\":\"starting\""), "{antwort}");
+
+        // Langsame Migration: jede Stufe bleibt eine Weile sichtbar
+        let migration = {
+            let health = health.clone();
+            tokio::spawn(async move {
+                for aktuell in 1..=3 {
+                    health.phase_setzen(StartPhase::SchemaMigration { aktuell, gesamt: 3 });
+                    tokio::time::sleep(Duration::from_millis(150)).await;
+                }
+                health.phase_setzen(StartPhase::DiensteErstellen);
+            })
+        };
+
+        let mut gesehen: Vec<String> = Vec::new();
+        while !migration.is_finished() {
+            let antwort = http_get(addr, "/health/ready").await;
+            assert!(antwort.starts_with("HTTP/1.1 503"), "{antwort}");
+            if let Some(detail) = ["1", "2", "3"]
+                .iter()
+                .map(|n| format!("migrating schema {n} of 3"))
+                .find(|detail| antwort.contains(detail.as_str()))
+            {
+                if gesehen.last() != Some(&detail) {
+                    gesehen.push(detail);
+                }
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        assert_eq!(
+            gesehen,
+            vec![
+                "migrating schema 1 of 3",
+                "migrating schema 2 of 3",
+                "migrating schema 3 of 3"
+            ]
+        );
+
+        let antwort = http_get(addr, "/health/ready").await;
+        assert!(antwort.contains("creating services"), "{antwort}");
+
+        health.phase_setzen(StartPhase::ListenerBinden);
+        let antwort = http_get(addr, "/health/ready").await;
+        assert!(antwort.contains("binding listeners"), "{antwort}");
+
+        // Startsequenz durch, aber ein Subsystem fehlt noch
+        health.phase_setzen(StartPhase::Abgeschlossen);
+        let antwort = http_get(addr, "/health/ready").await;
+        assert!(antwort.starts_with("HTTP/1.1 503"), "{antwort}");
+        assert!(antwort.contains("\"pending\":[\"voice\"]"), "{antwort}");
+
+        health.subsystem_bereit("voice");
+        let antwort = http_get(addr, "/health/ready").await;
+        assert!(antwort.starts_with("HTTP/1.1 200"), "{antwort}");
+        assert!(antwort.contains("\"status\":\"ok\""), "{antwort}");
+
+        shutdown_tx.send(true).unwrap();
+        assert!(server.await.unwrap().is_ok());
+    }
+
+    #[tokio::test]
+    async fn startfehler_bleibt_sichtbar() {
+        let health = HealthState::neu();
+        let addr = freie_adresse();
+        let (shutdown_tx, shutdown_rx) = watch::channel(false);
+        let server = tokio::spawn(observability_server_starten(
+            addr,
+            health.clone(),
+            shutdown_rx,
+        ));
+
+        health.phase_setzen(StartPhase::SchemaMigration {
+            aktuell: 1,
+            gesamt: 2,
+        });
+        health.start_fehlgeschlagen("Migration-Fehler: Datenbank gesperrt");
+
+        let antwort = http_get(addr, "/health/ready").await;
+        assert!(antwort.starts_with("HTTP/1.1 503"), "{antwort}");
+        assert!(antwort.contains("\"status\":\"failed\""), "{antwort}");
+        assert!(antwort.contains("Datenbank gesperrt"), "{antwort}");
+
+        // Liveness bleibt unberuehrt
+        let antwort = http_get(addr, "/health").await;
+        assert!(antwort.starts_with("HTTP/1.1 200"), "{antwort}");
+
+        shutdown_tx.send(true).unwrap();
+        assert!(server.await.unwrap().is_ok());
+    }
+}
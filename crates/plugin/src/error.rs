@@ -1,5 +1,6 @@
 //! Fehlertypen fuer das Plugin-System
 
+use speakeasy_core::SpeakeasyError;
 use thiserror::Error;
 
 /// Alle moeglichen Fehler im Plugin-System
@@ -80,6 +81,38 @@ pub enum PluginError {
 /// Result-Alias fuer das Plugin-System
 pub type Result<T> = std::result::Result<T, PluginError>;
 
+impl From<PluginError> for SpeakeasyError {
+    fn from(e: PluginError) -> Self {
+        match e {
+            PluginError::ManifestNichtGefunden(was)
+            | PluginError::FunktionNichtGefunden(was)
+            | PluginError::NichtGefunden(was) => Self::NichtGefunden(was),
+            PluginError::Manifest(grund) | PluginError::UngueltigeVersion(grund) => {
+                Self::UngueltigeEingabe(grund)
+            }
+            PluginError::FehlendeFaehigkeit(faehigkeit)
+            | PluginError::ZugriffVerweigert(faehigkeit) => Self::ZugriffVerweigert(faehigkeit),
+            PluginError::BereitsGeladen(name) => Self::Konflikt(name),
+            PluginError::Io(io) => io.into(),
+            PluginError::Intern(grund) => Self::Intern(grund),
+            PluginError::Anyhow(e) => Self::Anyhow(e),
+            PluginError::WasmLaden(_)
+            | PluginError::WasmKompilierung(_)
+            | PluginError::WasmInstanziierung(_)
+            | PluginError::WasmAusfuehrung(_)
+            | PluginError::SignaturUngueltig
+            | PluginError::NichtSigniert
+            | PluginError::SchluesselUngueltig(_)
+            | PluginError::NichtAktiv
+            | PluginError::Initialisierung(_)
+            | PluginError::Registry(_) => Self::Plugin {
+                name: "plugin-host".into(),
+                grund: e.to_string(),
+            },
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -108,4 +141,46 @@ mod tests {
         let plugin_err: PluginError = io_err.into();
         assert!(plugin_err.to_string().contains("IO-Fehler"));
     }
+
+    #[test]
+    fn jede_variante_hat_einen_fachlichen_code() {
+        use speakeasy_core::FehlerCode;
+
+        let varianten = vec![
+            PluginError::Manifest("x".into()),
+            PluginError::ManifestNichtGefunden("x".into()),
+            PluginError::UngueltigeVersion("x".into()),
+            PluginError::WasmLaden("x".into()),
+            PluginError::WasmKompilierung("x".into()),
+            PluginError::WasmInstanziierung("x".into()),
+            PluginError::WasmAusfuehrung("x".into()),
+            PluginError::FunktionNichtGefunden("x".into()),
+            PluginError::SignaturUngueltig,
+            PluginError::NichtSigniert,
+            PluginError::SchluesselUngueltig("x".into()),
+            PluginError::FehlendeFaehigkeit("x".into()),
+            PluginError::ZugriffVerweigert("x".into()),
+            PluginError::NichtGefunden("x".into()),
+            PluginError::BereitsGeladen("x".into()),
+            PluginError::NichtAktiv,
+            PluginError::Initialisierung("x".into()),
+            PluginError::Registry("x".into()),
+            PluginError::Io(std::io::Error::new(std::io::ErrorKind::NotFound, "x")),
+        ];
+        for e in varianten {
+            let anzeige = e.to_string();
+            assert_ne!(
+                SpeakeasyError::from(e).code(),
+                FehlerCode::Intern,
+                "{anzeige}"
+            );
+        }
+        // Bewusst intern: keine fachliche Zuordnung moeglich
+        for e in [
+            PluginError::Intern("x".into()),
+            PluginError::Anyhow(anyhow::anyhow!("x")),
+        ] {
+            assert_eq!(SpeakeasyError::from(e).code(), FehlerCode::Intern);
+        }
+    }
 }
@@ -0,0 +1,69 @@
+//! Erzeugt die Konformitaets-Testvektoren unter `crates/protocol/conformance/`
+//!
+//! Aufruf: `cargo run -p speakeasy-protocol --bin konformitaet`
+//!
+//! Existiert fuer die aktuelle Protokollversion bereits ein Manifest-Eintrag
+//! mit abweichendem Fingerabdruck, wird nichts geschrieben: geaenderte
+//! Vektoren erfordern eine neue `ProtokollVersion::AKTUELL`.
+
+use std::path::Path;
+use std::process::ExitCode;
+
+use speakeasy_protocol::conformance::{
+    als_json_datei, fingerabdruck, protokoll_version, vektor_dateien, Manifest, ManifestEintrag,
+    MANIFEST_DATEI, VEKTOR_VERZEICHNIS,
+};
+
+fn main() -> ExitCode {
+    let verzeichnis = Path::new(env!("CARGO_MANIFEST_DIR")).join(VEKTOR_VERZEICHNIS);
+    match erzeugen(&verzeichnis) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("Fehler: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn erzeugen(verzeichnis: &Path) -> Result<(), String> {
+    let dateien = vektor_dateien();
+    let version = protokoll_version();
+    let abdruck = fingerabdruck(&dateien);
+
+    let manifest_pfad = verzeichnis.join(MANIFEST_DATEI);
+    let mut manifest: Manifest = match std::fs::read_to_string(&manifest_pfad) {
+        Ok(inhalt) => serde_json::from_str(&inhalt)
+            .map_err(|e| format!("{} ist ungueltig: {e}", manifest_pfad.display()))?,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Manifest::default(),
+        Err(e) => return Err(format!("{}: {e}", manifest_pfad.display())),
+    };
+
+    match manifest.eintrag(&version) {
+        Some(eintrag) if eintrag.fingerabdruck != abdruck => {
+            return Err(format!(
+                "Vektoren fuer Protokollversion {version} haben sich geaendert \
+                 ({} -> {abdruck}). ProtokollVersion::AKTUELL erhoehen und erneut ausfuehren.",
+                eintrag.fingerabdruck
+            ));
+        }
+        Some(_) => {}
+        None => manifest.versionen.push(ManifestEintrag {
+            protokoll_version: version.clone(),
+            fingerabdruck: abdruck.clone(),
+        }),
+    }
+
+    std::fs::create_dir_all(verzeichnis).map_err(|e| format!("{}: {e}", verzeichnis.display()))?;
+    for (name, inhalt) in &dateien {
+        let pfad = verzeichnis.join(name);
+        std::fs::write(&pfad, inhalt).map_err(|e| format!("{}: {e}", pfad.display()))?;
+    }
+    std::fs::write(&manifest_pfad, als_json_datei(&manifest))
+        .map_err(|e| format!("{}: {e}", manifest_pfad.display()))?;
+
+    println!(
+        "{} Vektor-Dateien fuer Protokollversion {version} geschrieben ({abdruck})",
+        dateien.len()
+    );
+    Ok(())
+}
@@ -0,0 +1,318 @@
+//! Chat-Verlauf in Teilstuecken
+//!
+//! 50 Nachrichten mit langen Inhalten koennen sich der maximalen
+//! Frame-Groesse naehern, und der Client zeigt nichts an, bevor der ganze
+//! Frame angekommen ist. Mit `ChatHistoryRequest::chunked` sendet der Server
+//! den Verlauf deshalb als Folge von `ChatHistoryChunk` (gleiche
+//! `request_id` wie die Anfrage) und schliesst mit `ChatHistoryComplete` ab.
+//! Scheitert das Laden, endet die Folge mit einem `Error` fuer dieselbe
+//! `request_id`.
+//!
+//! Ein Teilstueck enthaelt hoechstens [`MAX_NACHRICHTEN_PRO_TEILSTUECK`]
+//! Nachrichten und (kodiert) hoechstens [`MAX_TEILSTUECK_BYTES`]. Eine
+//! Nachricht ist selbst im schlimmsten Fall (4096 Zeichen, jedes als
+//! `\u00XX` maskiert) rund 25 KiB gross und passt damit immer allein in ein
+//! Teilstueck.
+//!
+//! Clients pruefen die Folge mit [`VerlaufEmpfang`]: Teilstuecke muessen
+//! lueckenlos nummeriert sein und der Abschluss muss zu ihnen passen.
+
+use std::fmt;
+
+use crate::control::{
+    ChatHistoryChunk, ChatHistoryComplete, ChatHistoryResponse, ChatMessageInfo, ControlPayload,
+    ErrorResponse,
+};
+use crate::wire::DEFAULT_MAX_FRAME_SIZE;
+
+/// Hoechstens so viele Nachrichten pro Teilstueck
+pub const MAX_NACHRICHTEN_PRO_TEILSTUECK: usize = 10;
+
+/// Obergrenze fuer die kodierten Nachrichten eines Teilstuecks
+pub const MAX_TEILSTUECK_BYTES: usize = DEFAULT_MAX_FRAME_SIZE / 16;
+
+/// Teilt einen Verlauf (aelteste zuerst) in Teilstuecke auf
+///
+/// Reihenfolge und Vollstaendigkeit bleiben erhalten. Eine einzelne
+/// Nachricht ueber [`MAX_TEILSTUECK_BYTES`] bildet ein eigenes Teilstueck.
+pub fn aufteilen(nachrichten: Vec<ChatMessageInfo>) -> Vec<Vec<ChatMessageInfo>> {
+    let mut teilstuecke = Vec::new();
+    let mut aktuell: Vec<ChatMessageInfo> = Vec::new();
+    let mut bytes = 0;
+
+    for nachricht in nachrichten {
+        let groesse = kodierte_groesse(&nachricht);
+        let voll = aktuell.len() >= MAX_NACHRICHTEN_PRO_TEILSTUECK
+            || bytes + groesse > MAX_TEILSTUECK_BYTES;
+        if voll && !aktuell.is_empty() {
+            teilstuecke.push(std::mem::take(&mut aktuell));
+            bytes = 0;
+        }
+        bytes += groesse;
+        aktuell.push(nachricht);
+    }
+    if !aktuell.is_empty() {
+        teilstuecke.push(aktuell);
+    }
+    teilstuecke
+}
+
+fn kodierte_groesse(nachricht: &ChatMessageInfo) -> usize {
+    // Serialisierung eines einfachen Structs scheitert nicht; im Zweifel
+    // lieber ein eigenes Teilstueck
+    serde_json::to_vec(nachricht)
+        .map(|json| json.len())
+        .unwrap_or(MAX_TEILSTUECK_BYTES)
+}
+
+/// Naechster Schritt beim Empfang eines Verlaufs
+#[derive(Debug, Clone)]
+pub enum VerlaufSchritt {
+    /// Teilstueck zum sofortigen Anzeigen, weitere folgen
+    Teilstueck(ChatHistoryChunk),
+    /// Folge vollstaendig empfangen
+    Fertig(ChatHistoryComplete),
+    /// Server ohne Teilstueck-Unterstuetzung: ganzer Verlauf auf einmal
+    Gesamt(ChatHistoryResponse),
+}
+
+/// Abbruch beim Empfang eines Verlaufs
+#[derive(Debug, Clone)]
+pub enum VerlaufFehler {
+    /// Der Server hat die Folge mit einem Fehler beendet
+    Server(ErrorResponse),
+    /// Teilstueck ausserhalb der Reihenfolge
+    Reihenfolge { erwartet: u32, erhalten: u32 },
+    /// Abschluss passt nicht zu den empfangenen Teilstuecken
+    Unvollstaendig {
+        teilstuecke: u32,
+        nachrichten: u32,
+        abschluss: ChatHistoryComplete,
+    },
+    /// Nachricht gehoert nicht zu einem Chat-Verlauf
+    Unerwartet,
+}
+
+impl fmt::Display for VerlaufFehler {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Server(fehler) => write!(f, "Server-Fehler: {}", fehler.message),
+            Self::Reihenfolge { erwartet, erhalten } => write!(
+                f,
+                "Teilstueck {erhalten} statt {erwartet} empfangen"
+            ),
+            Self::Unvollstaendig {
+                teilstuecke,
+                nachrichten,
+                abschluss,
+            } => write!(
+                f,
+                "Verlauf unvollstaendig: {teilstuecke}/{} Teilstuecke, {nachrichten}/{} Nachrichten",
+                abschluss.chunks, abschluss.total
+            ),
+            Self::Unerwartet => write!(f, "Unerwartete Nachricht im Chat-Verlauf"),
+        }
+    }
+}
+
+impl std::error::Error for VerlaufFehler {}
+
+/// Empfaengerseite eines Verlaufs in Teilstuecken
+///
+/// Nimmt die Nachrichten zur `request_id` der Anfrage entgegen, bis
+/// [`VerlaufSchritt::Fertig`] (bzw. `Gesamt`) oder ein Fehler kommt.
+#[derive(Debug, Clone, Default)]
+pub struct VerlaufEmpfang {
+    teilstuecke: u32,
+    nachrichten: u32,
+}
+
+impl VerlaufEmpfang {
+    pub fn neu() -> Self {
+        Self::default()
+    }
+
+    /// Ordnet die naechste Nachricht der Folge ein
+    pub fn annehmen(&mut self, payload: ControlPayload) -> Result<VerlaufSchritt, VerlaufFehler> {
+        match payload {
+            ControlPayload::ChatHistoryChunk(teil) => {
+                if teil.index != self.teilstuecke {
+                    return Err(VerlaufFehler::Reihenfolge {
+                        erwartet: self.teilstuecke,
+                        erhalten: teil.index,
+                    });
+                }
+                self.teilstuecke += 1;
+                self.nachrichten += teil.messages.len() as u32;
+                Ok(VerlaufSchritt::Teilstueck(teil))
+            }
+            ControlPayload::ChatHistoryComplete(abschluss) => {
+                if abschluss.chunks != self.teilstuecke || abschluss.total != self.nachrichten {
+                    return Err(VerlaufFehler::Unvollstaendig {
+                        teilstuecke: self.teilstuecke,
+                        nachrichten: self.nachrichten,
+                        abschluss,
+                    });
+                }
+                Ok(VerlaufSchritt::Fertig(abschluss))
+            }
+            ControlPayload::ChatHistoryResponse(antwort) if self.teilstuecke == 0 => {
+                Ok(VerlaufSchritt::Gesamt(antwort))
+            }
+            ControlPayload::Error(fehler) => Err(VerlaufFehler::Server(fehler)),
+            _ => Err(VerlaufFehler::Unerwartet),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use speakeasy_core::types::{ChannelId, UserId};
+
+    fn nachricht(nr: usize, inhalt: String) -> ChatMessageInfo {
+        ChatMessageInfo {
+            message_id: format!("nachricht-{nr}"),
+            channel_id: ChannelId(uuid::Uuid::nil()),
+            sender_id: UserId(uuid::Uuid::nil()),
+            content: inhalt,
+            message_type: "text".into(),
+            reply_to: None,
+            created_at: "2023-11-14T22:13:20Z".into(),
+            edited_at: None,
+        }
+    }
+
+    fn ids(teilstuecke: &[Vec<ChatMessageInfo>]) -> Vec<String> {
+        teilstuecke
+            .iter()
+            .flatten()
+            .map(|n| n.message_id.clone())
+            .collect()
+    }
+
+    #[test]
+    fn reihenfolge_und_vollstaendigkeit_bleiben_erhalten() {
+        let nachrichten: Vec<_> = (0..25).map(|nr| nachricht(nr, "Hallo".into())).collect();
+        let erwartet: Vec<_> = nachrichten.iter().map(|n| n.message_id.clone()).collect();
+
+        let teilstuecke = aufteilen(nachrichten);
+        assert_eq!(
+            teilstuecke.iter().map(Vec::len).collect::<Vec<_>>(),
+            vec![10, 10, 5]
+        );
+        assert_eq!(ids(&teilstuecke), erwartet);
+        assert!(aufteilen(Vec::new()).is_empty());
+    }
+
+    fn folge(nachrichten: usize) -> Vec<ControlPayload> {
+        let kanal = ChannelId(uuid::Uuid::nil());
+        let teile = aufteilen(
+            (0..nachrichten)
+                .map(|nr| nachricht(nr, "Hallo".into()))
+                .collect(),
+        );
+        let mut folge: Vec<_> = teile
+            .iter()
+            .cloned()
+            .enumerate()
+            .map(|(index, messages)| {
+                ControlPayload::ChatHistoryChunk(ChatHistoryChunk {
+                    channel_id: kanal,
+                    index: index as u32,
+                    messages,
+                })
+            })
+            .collect();
+        folge.push(ControlPayload::ChatHistoryComplete(ChatHistoryComplete {
+            channel_id: kanal,
+            chunks: teile.len() as u32,
+            total: nachrichten as u32,
+            next_before: None,
+        }));
+        folge
+    }
+
+    #[test]
+    fn empfang_einer_vollstaendigen_folge() {
+        let mut empfang = VerlaufEmpfang::neu();
+        let mut ids = Vec::new();
+        for payload in folge(25) {
+            match empfang.annehmen(payload).unwrap() {
+                VerlaufSchritt::Teilstueck(teil) => {
+                    ids.extend(teil.messages.into_iter().map(|n| n.message_id))
+                }
+                VerlaufSchritt::Fertig(abschluss) => assert_eq!(abschluss.total, 25),
+                VerlaufSchritt::Gesamt(_) => panic!("keine Gesamtantwort erwartet"),
+            }
+        }
+        let erwartet: Vec<_> = (0..25).map(|nr| format!("nachricht-{nr}")).collect();
+        assert_eq!(ids, erwartet);
+    }
+
+    #[test]
+    fn luecken_und_fehlende_teile_werden_erkannt() {
+        let mut folge = folge(25);
+        let mut empfang = VerlaufEmpfang::neu();
+        empfang.annehmen(folge.remove(0)).unwrap();
+        assert!(matches!(
+            empfang.annehmen(folge.remove(1)),
+            Err(VerlaufFehler::Reihenfolge {
+                erwartet: 1,
+                erhalten: 2
+            })
+        ));
+
+        let mut empfang = VerlaufEmpfang::neu();
+        let abschluss = folge.pop().unwrap();
+        assert!(matches!(
+            empfang.annehmen(abschluss),
+            Err(VerlaufFehler::Unvollstaendig { teilstuecke: 0, .. })
+        ));
+    }
+
+    #[test]
+    fn fehler_mitten_in_der_folge_beendet_den_empfang() {
+        let mut folge = folge(25);
+        let mut empfang = VerlaufEmpfang::neu();
+        assert!(matches!(
+            empfang.annehmen(folge.remove(0)),
+            Ok(VerlaufSchritt::Teilstueck(_))
+        ));
+        let fehler = crate::control::ControlMessage::error(
+            7,
+            crate::control::ErrorCode::InternalError,
+            "History konnte nicht geladen werden",
+        );
+        match empfang.annehmen(fehler.payload) {
+            Err(VerlaufFehler::Server(fehler)) => {
+                assert_eq!(fehler.message, "History konnte nicht geladen werden")
+            }
+            andere => panic!("unerwartet: {andere:?}"),
+        }
+        // Eine Gesamtantwort nach Teilstuecken gehoert nicht zur Folge
+        let gesamt = ControlPayload::ChatHistoryResponse(ChatHistoryResponse {
+            channel_id: ChannelId(uuid::Uuid::nil()),
+            messages: Vec::new(),
+        });
+        assert!(matches!(
+            empfang.annehmen(gesamt),
+            Err(VerlaufFehler::Unerwartet)
+        ));
+    }
+
+    #[test]
+    fn schlimmster_fall_bleibt_unter_der_frame_groesse() {
+        // Steuerzeichen werden als \u00XX maskiert: sechsfache Groesse
+        let inhalt = "\u{1}".repeat(4096);
+        let nachrichten: Vec<_> = (0..50).map(|nr| nachricht(nr, inhalt.clone())).collect();
+
+        let teilstuecke = aufteilen(nachrichten);
+        assert_eq!(ids(&teilstuecke).len(), 50);
+        for teilstueck in &teilstuecke {
+            let bytes = serde_json::to_vec(teilstueck).unwrap().len();
+            assert!(bytes <= MAX_TEILSTUECK_BYTES + 64, "{bytes} Bytes");
+            assert!(bytes < DEFAULT_MAX_FRAME_SIZE / 8);
+        }
+    }
+}
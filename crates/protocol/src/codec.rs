@@ -150,6 +150,14 @@ pub enum AudioPreset {
 }
 
 impl AudioPreset {
+    /// Alle Presets, absteigend nach Bitrate
+    pub const ALLE: [AudioPreset; 4] = [
+        AudioPreset::Music,
+        AudioPreset::Balanced,
+        AudioPreset::Speech,
+        AudioPreset::LowBandwidth,
+    ];
+
     /// Gibt die vordefinierte `OpusConfig` fuer dieses Preset zurueck
     pub fn config(&self) -> OpusConfig {
         match self {
@@ -200,6 +208,26 @@ impl AudioPreset {
         }
     }
 
+    /// Erlaubter Bereich (kbps) fuer die adaptive Bitrate
+    ///
+    /// Die Obergrenze ist die Bitrate des Presets; darunter bleibt genug
+    /// Spielraum, ohne dass der Anwendungsfall unbrauchbar wird.
+    pub fn bitrate_grenzen(&self) -> (u16, u16) {
+        let untergrenze = match self {
+            AudioPreset::Speech => 12,
+            AudioPreset::Balanced => 16,
+            AudioPreset::Music => 48,
+            AudioPreset::LowBandwidth => 6,
+        };
+        (untergrenze, self.config().bitrate_kbps)
+    }
+
+    /// Begrenzt eine Ziel-Bitrate auf [`AudioPreset::bitrate_grenzen`]
+    pub fn bitrate_begrenzen(&self, kbps: u16) -> u16 {
+        let (min, max) = self.bitrate_grenzen();
+        kbps.clamp(min, max)
+    }
+
     /// Gibt den menschenlesbaren Namen des Presets zurueck
     pub fn bezeichnung(&self) -> &'static str {
         match self {
@@ -220,7 +248,7 @@ impl AudioPreset {
 /// Der Client sendet eine `CodecNegotiationRequest` mit der gewuenschten
 /// Konfiguration. Der Server antwortet mit einer `CodecNegotiationResponse`
 /// die die erlaubte (moeglicherweise angepasste) Konfiguration enthaelt.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct CodecNegotiationRequest {
     /// Gewuenschte Konfiguration des Clients
     pub requested: OpusConfig,
@@ -234,6 +262,34 @@ pub struct CodecNegotiationRequest {
     pub max_download_kbps: u16,
 }
 
+impl CodecNegotiationRequest {
+    /// Anfrage fuer ein Preset ohne clientseitige Bitrate-Grenze
+    ///
+    /// Opus kodiert alle Abtastraten; die des Presets steht vorne.
+    pub fn fuer_preset(preset: AudioPreset) -> Self {
+        let requested = preset.config();
+        let mut supported_sample_rates = vec![requested.sample_rate];
+        supported_sample_rates.extend(
+            [
+                SampleRate::Hz48000,
+                SampleRate::Hz24000,
+                SampleRate::Hz16000,
+                SampleRate::Hz12000,
+                SampleRate::Hz8000,
+            ]
+            .into_iter()
+            .filter(|rate| *rate != requested.sample_rate),
+        );
+        Self {
+            requested,
+            preset_hint: Some(preset),
+            supported_sample_rates,
+            max_upload_kbps: 510,
+            max_download_kbps: 510,
+        }
+    }
+}
+
 /// Status der Codec-Aushandlung
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
@@ -247,7 +303,7 @@ pub enum NegotiationStatus {
 }
 
 /// Antwort des Servers auf eine Codec-Aushandlung
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct CodecNegotiationResponse {
     /// Status der Aushandlung
     pub status: NegotiationStatus,
@@ -257,6 +313,10 @@ pub struct CodecNegotiationResponse {
     pub adjustment_reason: Option<String>,
     /// Maximale erlaubte Bitrate des Servers (serverseitige Begrenzung)
     pub server_max_bitrate_kbps: u16,
+    /// Preset, aus dem `accepted` hervorgeht (`None` = eigene Konfiguration);
+    /// seine Grenzen gelten fuer die adaptive Bitrate
+    #[serde(default)]
+    pub preset: Option<AudioPreset>,
 }
 
 // ---------------------------------------------------------------------------
@@ -325,6 +385,28 @@ mod tests {
         }
     }
 
+    #[test]
+    fn bitrate_wird_auf_preset_grenzen_begrenzt() {
+        assert_eq!(AudioPreset::Balanced.bitrate_begrenzen(36), 36);
+        assert_eq!(AudioPreset::Balanced.bitrate_begrenzen(8), 16);
+        assert_eq!(AudioPreset::Balanced.bitrate_begrenzen(510), 64);
+        assert_eq!(AudioPreset::Speech.bitrate_begrenzen(6), 12);
+        assert_eq!(AudioPreset::LowBandwidth.bitrate_begrenzen(0), 6);
+
+        for preset in [
+            AudioPreset::Speech,
+            AudioPreset::Balanced,
+            AudioPreset::Music,
+            AudioPreset::LowBandwidth,
+        ] {
+            let (min, max) = preset.bitrate_grenzen();
+            assert!(min <= max, "{preset:?}");
+            let mut config = preset.config();
+            config.bitrate_kbps = min;
+            assert!(config.validieren().is_ok(), "{preset:?}");
+        }
+    }
+
     #[test]
     fn opus_config_validierung_ungueltige_bitrate() {
         let mut config = AudioPreset::Speech.config();
@@ -382,11 +464,41 @@ mod tests {
             accepted: AudioPreset::Speech.config(),
             adjustment_reason: Some("Bitrate auf Server-Maximum reduziert".to_string()),
             server_max_bitrate_kbps: 32,
+            preset: Some(AudioPreset::Speech),
         };
         let json = serde_json::to_string(&resp).unwrap();
         let decoded: CodecNegotiationResponse = serde_json::from_str(&json).unwrap();
-        assert_eq!(decoded.status, NegotiationStatus::Adjusted);
-        assert!(decoded.adjustment_reason.is_some());
+        assert_eq!(decoded, resp);
+
+        // Antworten ohne Preset (aeltere Server) bleiben lesbar
+        let mut wert = serde_json::to_value(&resp).unwrap();
+        wert.as_object_mut().unwrap().remove("preset");
+        let alt: CodecNegotiationResponse = serde_json::from_value(wert).unwrap();
+        assert_eq!(alt.preset, None);
+    }
+
+    #[test]
+    fn anfrage_fuer_preset() {
+        let req = CodecNegotiationRequest::fuer_preset(AudioPreset::Speech);
+        assert_eq!(req.requested, AudioPreset::Speech.config());
+        assert_eq!(req.preset_hint, Some(AudioPreset::Speech));
+        assert_eq!(req.supported_sample_rates[0], SampleRate::Hz16000);
+        assert_eq!(req.supported_sample_rates.len(), 5);
+
+        let json = serde_json::to_string(&req).unwrap();
+        assert_eq!(
+            serde_json::from_str::<CodecNegotiationRequest>(&json).unwrap(),
+            req
+        );
+    }
+
+    #[test]
+    fn alle_presets_absteigend_nach_bitrate() {
+        let bitraten: Vec<u16> = AudioPreset::ALLE
+            .iter()
+            .map(|p| p.config().bitrate_kbps)
+            .collect();
+        assert!(bitraten.windows(2).all(|w| w[0] > w[1]));
     }
 
     #[test]
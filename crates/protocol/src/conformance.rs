@@ -0,0 +1,1389 @@
+//! Konformitaets-Testvektoren fuer alternative Implementierungen
+//!
+//! Erzeugt die kanonischen Kodierungen, die im Verzeichnis `conformance/`
+//! des Protokoll-Crates abgelegt werden:
+//!
+//! - `control.json` – JSON-Kodierung jeder `ControlPayload`-Variante
+//! - `voice.json`   – Hex-Dumps von Voice-Paketen (alle Typen, Flag-Kombinationen)
+//! - `wire.json`    – Byte-Stroeme des Frame-Codecs inkl. Grenzfaellen
+//! - `manifest.json` – Fingerabdruck der Vektoren je Protokollversion
+//!
+//! Die Dateien werden mit `cargo run -p speakeasy-protocol --bin konformitaet`
+//! erzeugt. Die Integrationstests vergleichen sie byteweise mit der aktuellen
+//! Kodierung. Aendern sich die Vektoren, muss `ProtokollVersion::AKTUELL`
+//! im selben Commit erhoeht werden (siehe `conformance/README.md`).
+
+use serde::{Deserialize, Serialize};
+use speakeasy_core::types::{ChannelId, ServerId, UserId};
+use uuid::Uuid;
+
+use crate::codec::{
+    AudioPreset, CodecNegotiationRequest, CodecNegotiationResponse, NegotiationStatus,
+};
+use crate::control::*;
+use crate::crypto::{AeadAlgorithm, E2EKeyMessage, KeyPurpose, KeyRotationReason};
+use crate::voice::{
+    HelloPaket, PacketType, PingPaket, VoiceFlags, VoicePacket, VoicePacketHeader,
+    MAX_NUTZDATEN_LAENGE,
+};
+use crate::wire::LENGTH_FIELD_SIZE;
+
+/// Verzeichnis der Vektor-Dateien (relativ zum Crate-Root)
+pub const VEKTOR_VERZEICHNIS: &str = "conformance";
+
+/// Dateiname des Manifests
+pub const MANIFEST_DATEI: &str = "manifest.json";
+
+/// Maximale Frame-Groesse fuer die Wire-Vektoren
+///
+/// Bewusst klein gewaehlt, damit Grenzfaelle als Hex-Dump handhabbar bleiben.
+pub const WIRE_MAX_FRAME_SIZE: usize = 256;
+
+// ---------------------------------------------------------------------------
+// Vektor-Formate
+// ---------------------------------------------------------------------------
+
+/// Kanonische JSON-Kodierung einer Control-Nachricht
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ControlVektor {
+    /// Name der `ControlPayload`-Variante (snake_case, identisch zum `type`-Tag)
+    pub name: String,
+    /// Kompakte JSON-Kodierung der vollstaendigen `ControlMessage`
+    pub json: String,
+}
+
+/// Erwartetes Ergebnis beim Dekodieren eines Byte-Vektors
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Erwartung {
+    /// Dekodierung erfolgreich
+    Ok,
+    /// Decoder wartet auf weitere Bytes
+    Unvollstaendig,
+    /// Decoder muss einen Fehler melden
+    Fehler,
+}
+
+/// Hex-Dump eines Voice-Pakets
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VoiceVektor {
+    pub name: String,
+    pub hex: String,
+    pub erwartung: Erwartung,
+}
+
+/// Byte-Strom des Frame-Codecs
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WireVektor {
+    pub name: String,
+    pub hex: String,
+    pub erwartung: Erwartung,
+    /// Anzahl vollstaendig dekodierter Nachrichten vor Ende bzw. Fehler
+    pub nachrichten: usize,
+}
+
+/// Inhalt von `wire.json`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WireVektoren {
+    pub max_frame_size: usize,
+    pub vektoren: Vec<WireVektor>,
+}
+
+/// Fingerabdruck der Vektoren fuer eine Protokollversion
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ManifestEintrag {
+    pub protokoll_version: String,
+    pub fingerabdruck: String,
+}
+
+/// Inhalt von `manifest.json`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Manifest {
+    pub versionen: Vec<ManifestEintrag>,
+}
+
+impl Manifest {
+    /// Sucht den Eintrag fuer eine Protokollversion
+    pub fn eintrag(&self, version: &str) -> Option<&ManifestEintrag> {
+        self.versionen
+            .iter()
+            .find(|e| e.protokoll_version == version)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Erzeugung
+// ---------------------------------------------------------------------------
+
+/// Aktuelle Protokollversion als "major.minor"
+pub fn protokoll_version() -> String {
+    format!(
+        "{}.{}",
+        ProtokollVersion::AKTUELL.major,
+        ProtokollVersion::AKTUELL.minor
+    )
+}
+
+/// Alle Vektor-Dateien (Name, Inhalt) in fester Reihenfolge, ohne Manifest
+pub fn vektor_dateien() -> Vec<(&'static str, String)> {
+    let control: Vec<ControlVektor> = beispiel_nachrichten()
+        .into_iter()
+        .map(|msg| ControlVektor {
+            name: variante_name(&msg.payload).to_string(),
+            json: msg.to_json().expect("Beispielnachricht ist serialisierbar"),
+        })
+        .collect();
+
+    vec![
+        ("control.json", als_json_datei(&control)),
+        ("voice.json", als_json_datei(&voice_vektoren())),
+        (
+            "wire.json",
+            als_json_datei(&WireVektoren {
+                max_frame_size: WIRE_MAX_FRAME_SIZE,
+                vektoren: wire_vektoren(),
+            }),
+        ),
+    ]
+}
+
+/// Fingerabdruck ueber alle Vektor-Dateien (FNV-1a, 64 Bit)
+pub fn fingerabdruck(dateien: &[(&str, String)]) -> String {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for (name, inhalt) in dateien {
+        for byte in name.bytes().chain([0]).chain(inhalt.bytes()).chain([0]) {
+            hash ^= byte as u64;
+            hash = hash.wrapping_mul(0x0000_0100_0000_01b3);
+        }
+    }
+    format!("fnv1a64:{hash:016x}")
+}
+
+/// Serialisiert einen Wert als formatierte JSON-Datei mit abschliessendem Zeilenumbruch
+pub fn als_json_datei<T: Serialize>(wert: &T) -> String {
+    let mut s = serde_json::to_string_pretty(wert).expect("Vektoren sind serialisierbar");
+    s.push('\n');
+    s
+}
+
+/// Kodiert Bytes als Kleinbuchstaben-Hex
+pub fn hex_kodieren(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+/// Dekodiert einen Hex-String
+pub fn hex_dekodieren(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).ok())
+        .collect()
+}
+
+/// Name der Variante, identisch zum serde-Tag
+///
+/// Bewusst ohne Wildcard: eine neue Variante bricht hier den Build,
+/// bis ein Beispiel in `beispiel_nachrichten` ergaenzt wurde.
+pub fn variante_name(payload: &ControlPayload) -> &'static str {
+    match payload {
+        ControlPayload::Hello(_) => "hello",
+        ControlPayload::Welcome(_) => "welcome",
+        ControlPayload::Login(_) => "login",
+        ControlPayload::LoginResponse(_) => "login_response",
+        ControlPayload::Register(_) => "register",
+        ControlPayload::RegisterResponse(_) => "register_response",
+        ControlPayload::Logout(_) => "logout",
+        ControlPayload::LogoutResponse(_) => "logout_response",
+        ControlPayload::SessionExpiryWarning(_) => "session_expiry_warning",
+        ControlPayload::SessionRenew => "session_renew",
+        ControlPayload::SessionRenewResponse(_) => "session_renew_response",
+        ControlPayload::PasswordChange(_) => "password_change",
+        ControlPayload::PasswordChangeResponse(_) => "password_change_response",
+        ControlPayload::NicknameChange(_) => "nickname_change",
+        ControlPayload::NicknameChangeResponse(_) => "nickname_change_response",
+        ControlPayload::SetAway(_) => "set_away",
+        ControlPayload::SetAwayResponse(_) => "set_away_response",
+        ControlPayload::AccountDataExport => "account_data_export",
+        ControlPayload::AccountDataExportResponse(_) => "account_data_export_response",
+        ControlPayload::AccountExportReady(_) => "account_export_ready",
+        ControlPayload::AccountDelete(_) => "account_delete",
+        ControlPayload::AccountDeleteResponse(_) => "account_delete_response",
+        ControlPayload::ChannelList(_) => "channel_list",
+        ControlPayload::ChannelListResponse(_) => "channel_list_response",
+        ControlPayload::ChannelTreeExpand(_) => "channel_tree_expand",
+        ControlPayload::ChannelJoin(_) => "channel_join",
+        ControlPayload::ChannelJoinResponse(_) => "channel_join_response",
+        ControlPayload::ChannelMembers(_) => "channel_members",
+        ControlPayload::ChannelMembersResponse(_) => "channel_members_response",
+        ControlPayload::ChannelLeave(_) => "channel_leave",
+        ControlPayload::ChannelCreate(_) => "channel_create",
+        ControlPayload::ChannelCreateResponse(_) => "channel_create_response",
+        ControlPayload::ChannelEdit(_) => "channel_edit",
+        ControlPayload::ChannelEdited(_) => "channel_edited",
+        ControlPayload::ChannelDelete(_) => "channel_delete",
+        ControlPayload::ChannelTreeChanged(_) => "channel_tree_changed",
+        ControlPayload::ChannelEmergencyMute(_) => "channel_emergency_mute",
+        ControlPayload::ChannelEmergencyMuteEvent(_) => "channel_emergency_mute_event",
+        ControlPayload::ChannelInvite(_) => "channel_invite",
+        ControlPayload::ChannelInviteResponse(_) => "channel_invite_response",
+        ControlPayload::ChannelInviteReceived(_) => "channel_invite_received",
+        ControlPayload::ChannelInviteAnswer(_) => "channel_invite_answer",
+        ControlPayload::ChannelInviteResult(_) => "channel_invite_result",
+        ControlPayload::ChannelKnock(_) => "channel_knock",
+        ControlPayload::ChannelKnockResponse(_) => "channel_knock_response",
+        ControlPayload::ChannelKnockReceived(_) => "channel_knock_received",
+        ControlPayload::ChannelKnockAnswer(_) => "channel_knock_answer",
+        ControlPayload::ChannelKnockResult(_) => "channel_knock_result",
+        ControlPayload::SoundboardPlay(_) => "soundboard_play",
+        ControlPayload::SoundboardStop(_) => "soundboard_stop",
+        ControlPayload::SoundboardPlayback(_) => "soundboard_playback",
+        ControlPayload::ClientList => "client_list",
+        ControlPayload::ClientListResponse(_) => "client_list_response",
+        ControlPayload::ClientKick(_) => "client_kick",
+        ControlPayload::ClientBan(_) => "client_ban",
+        ControlPayload::ClientMove(_) => "client_move",
+        ControlPayload::ClientMoved(_) => "client_moved",
+        ControlPayload::ClientsMoveAll(_) => "clients_move_all",
+        ControlPayload::ClientsMoveAllResponse(_) => "clients_move_all_response",
+        ControlPayload::ClientsMoved(_) => "clients_moved",
+        ControlPayload::ClientConnected(_) => "client_connected",
+        ControlPayload::ClientDisconnected(_) => "client_disconnected",
+        ControlPayload::ClientJoinedChannel(_) => "client_joined_channel",
+        ControlPayload::ClientLeftChannel(_) => "client_left_channel",
+        ControlPayload::ClientVoiceUpdated(_) => "client_voice_updated",
+        ControlPayload::ClientSpeaking(_) => "client_speaking",
+        ControlPayload::ClientPoke(_) => "client_poke",
+        ControlPayload::ClientUpdate(_) => "client_update",
+        ControlPayload::ClientUpdated(_) => "client_updated",
+        ControlPayload::ResolveIds(_) => "resolve_ids",
+        ControlPayload::ResolveIdsResponse(_) => "resolve_ids_response",
+        ControlPayload::ClientActivity => "client_activity",
+        ControlPayload::StateDiff(_) => "state_diff",
+        ControlPayload::StateDiffResponse(_) => "state_diff_response",
+        ControlPayload::ServerInfo => "server_info",
+        ControlPayload::ServerInfoResponse(_) => "server_info_response",
+        ControlPayload::ServerEdit(_) => "server_edit",
+        ControlPayload::ServerStop(_) => "server_stop",
+        ControlPayload::PendingDisconnectNotice(_) => "pending_disconnect_notice",
+        ControlPayload::CancelPendingDisconnect(_) => "cancel_pending_disconnect",
+        ControlPayload::PendingDisconnectCancelled(_) => "pending_disconnect_cancelled",
+        ControlPayload::SessionCapture(_) => "session_capture",
+        ControlPayload::SessionCaptureResponse(_) => "session_capture_response",
+        ControlPayload::MotdChanged(_) => "motd_changed",
+        ControlPayload::ServerAnnouncement(_) => "server_announcement",
+        ControlPayload::PermissionList { .. } => "permission_list",
+        ControlPayload::PermissionListResponse(_) => "permission_list_response",
+        ControlPayload::PermissionAdd(_) => "permission_add",
+        ControlPayload::PermissionRemove(_) => "permission_remove",
+        ControlPayload::EffectivePermissions(_) => "effective_permissions",
+        ControlPayload::EffectivePermissionsResponse(_) => "effective_permissions_response",
+        ControlPayload::FileList { .. } => "file_list",
+        ControlPayload::FileListResponse(_) => "file_list_response",
+        ControlPayload::FileUpload(_) => "file_upload",
+        ControlPayload::FileUploadResponse(_) => "file_upload_response",
+        ControlPayload::FileDownload(_) => "file_download",
+        ControlPayload::FileDownloadResponse(_) => "file_download_response",
+        ControlPayload::FileDelete(_) => "file_delete",
+        ControlPayload::ChatSend(_) => "chat_send",
+        ControlPayload::ChatSendResponse(_) => "chat_send_response",
+        ControlPayload::ChatEdit(_) => "chat_edit",
+        ControlPayload::ChatDelete(_) => "chat_delete",
+        ControlPayload::ChatHistory(_) => "chat_history",
+        ControlPayload::ChatHistoryResponse(_) => "chat_history_response",
+        ControlPayload::ChatHistoryChunk(_) => "chat_history_chunk",
+        ControlPayload::ChatHistoryComplete(_) => "chat_history_complete",
+        ControlPayload::ChatSearch(_) => "chat_search",
+        ControlPayload::ChatSearchResponse(_) => "chat_search_response",
+        ControlPayload::ChatMessage(_) => "chat_message",
+        ControlPayload::ChatEdited(_) => "chat_edited",
+        ControlPayload::ChatDeleted(_) => "chat_deleted",
+        ControlPayload::ChatBulkDeleted(_) => "chat_bulk_deleted",
+        ControlPayload::VoiceInit(_) => "voice_init",
+        ControlPayload::VoiceReady(_) => "voice_ready",
+        ControlPayload::VoiceDisconnect(_) => "voice_disconnect",
+        ControlPayload::VoiceStats(_) => "voice_stats",
+        ControlPayload::VoiceStatsResponse(_) => "voice_stats_response",
+        ControlPayload::VoiceQualityUpdate(_) => "voice_quality_update",
+        ControlPayload::E2EKeyRotationRequired(_) => "e2e_key_rotation_required",
+        ControlPayload::E2EKey(_) => "e2e_key",
+        ControlPayload::Ping(_) => "ping",
+        ControlPayload::Pong(_) => "pong",
+        ControlPayload::Error(_) => "error",
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Beispielwerte
+// ---------------------------------------------------------------------------
+
+fn user_id(n: u128) -> UserId {
+    UserId(Uuid::from_u128(
+        0x1000_0000_0000_4000_8000_0000_0000_0000 | n,
+    ))
+}
+
+fn channel_id(n: u128) -> ChannelId {
+    ChannelId(Uuid::from_u128(
+        0x2000_0000_0000_4000_8000_0000_0000_0000 | n,
+    ))
+}
+
+fn server_id() -> ServerId {
+    ServerId(Uuid::from_u128(0x3000_0000_0000_4000_8000_0000_0000_0001))
+}
+
+fn client_info(n: u128, kanal: Option<ChannelId>) -> ClientInfo {
+    ClientInfo {
+        user_id: user_id(n),
+        username: format!("user{n}"),
+        display_name: format!("Benutzer {n}"),
+        channel_id: kanal,
+        server_groups: vec!["Guest".into()],
+        is_muted: false,
+        is_deafened: n.is_multiple_of(2),
+        is_input_muted: true,
+        ssrc: n.is_multiple_of(2).then_some(0x1000 + n as u32),
+        listen_only: n.is_multiple_of(3),
+        soundboard: false,
+    }
+}
+
+/// Repraesentative Nachricht fuer jede `ControlPayload`-Variante
+///
+/// Optionale Felder werden gemischt mit `Some` und `None` belegt, damit
+/// beide Kodierungen (Wert und `null`) in den Vektoren vorkommen.
+pub fn beispiel_nachrichten() -> Vec<ControlMessage> {
+    let payloads = vec![
+        ControlPayload::Hello(HelloRequest {
+            protocol_version: ProtokollVersion {
+                major: 1,
+                minor: 26,
+            },
+            capabilities: vec!["chat".into(), "voice".into()],
+            client_name: "speakeasy-client/0.1.0".into(),
+        }),
+        ControlPayload::Welcome(WelcomeResponse {
+            protocol_version: ProtokollVersion {
+                major: 1,
+                minor: 26,
+            },
+            min_client_version: ProtokollVersion { major: 1, minor: 0 },
+            capabilities: vec!["chat".into(), "notifications".into()],
+            server_version: "0.1.0".into(),
+            max_message_chars: 4096,
+        }),
+        ControlPayload::Login(LoginRequest {
+            username: "alice".into(),
+            password: "geheim".into(),
+            token: None,
+            client_version: "1.0.0".into(),
+            display_name: Some("Alice".into()),
+            capture_consent: true,
+        }),
+        ControlPayload::LoginResponse(LoginResponse {
+            user_id: user_id(1),
+            session_token: "sitzung-abc".into(),
+            server_id: server_id(),
+            expires_at: 1_700_003_600,
+            server_groups: vec!["Admin".into(), "Guest".into()],
+            must_change_password: false,
+            welcome_message: Some("Willkommen auf dem Testserver".into()),
+            motd: Some(Motd {
+                markdown: "**Wartung** am Freitag ab 22 Uhr".into(),
+                version: 3,
+            }),
+        }),
+        ControlPayload::Register(RegisterRequest {
+            username: "carla".into(),
+            password: "geheim".into(),
+            invite_code: Some("K7QX2M9A".into()),
+            display_name: Some("Carla".into()),
+        }),
+        ControlPayload::RegisterResponse(RegisterResponse {
+            user_id: user_id(3),
+            username: "carla".into(),
+            display_name: Some("Carla".into()),
+            server_groups: vec!["Mitglieder".into()],
+        }),
+        ControlPayload::Logout(LogoutRequest {
+            reason: Some("Feierabend".into()),
+        }),
+        ControlPayload::LogoutResponse(LogoutResponse { success: true }),
+        ControlPayload::SessionExpiryWarning(SessionExpiryWarningEvent {
+            expires_in_secs: 600,
+        }),
+        ControlPayload::SessionRenew,
+        ControlPayload::SessionRenewResponse(SessionRenewResponse {
+            expires_at: 1_700_086_400,
+        }),
+        ControlPayload::PasswordChange(PasswordChangeRequest {
+            old_password: "alt".into(),
+            new_password: "neu".into(),
+        }),
+        ControlPayload::PasswordChangeResponse(PasswordChangeResponse { success: true }),
+        ControlPayload::NicknameChange(NicknameChangeRequest {
+            new_nickname: "Ali".into(),
+        }),
+        ControlPayload::NicknameChangeResponse(NicknameChangeResponse {
+            nickname: "Ali".into(),
+        }),
+        ControlPayload::SetAway(SetAwayRequest {
+            away: true,
+            message: Some("Kaffee".into()),
+        }),
+        ControlPayload::SetAwayResponse(SetAwayResponse { away: true }),
+        ControlPayload::AccountDataExport,
+        ControlPayload::AccountDataExportResponse(AccountDataExportResponse {
+            export_id: "export-1".into(),
+        }),
+        ControlPayload::AccountExportReady(AccountExportReadyEvent {
+            export_id: "export-1".into(),
+            download_url: "https://example.invalid/files/export/einmal-token".into(),
+            expires_at: 1_700_086_400,
+            size_bytes: 20_480,
+        }),
+        ControlPayload::AccountDelete(AccountDeleteRequest {
+            password_confirmation: "geheim".into(),
+        }),
+        ControlPayload::AccountDeleteResponse(AccountDeleteResponse { success: true }),
+        ControlPayload::ChannelList(ChannelListRequest {
+            parent_id: Some(channel_id(1)),
+            depth: Some(2),
+        }),
+        ControlPayload::ChannelListResponse(ChannelListResponse {
+            channels: vec![
+                ChannelInfo {
+                    channel_id: channel_id(1),
+                    name: "Lobby".into(),
+                    description: Some("Willkommen".into()),
+                    parent_id: None,
+                    sort_order: 0,
+                    max_clients: None,
+                    current_clients: 2,
+                    password_protected: false,
+                    codec: "opus".into(),
+                    codec_quality: 10,
+                    has_children: false,
+                    child_count: 1,
+                    slow_mode_secs: 0,
+                    edit_window_secs: 0,
+                    join_by_approval: false,
+                },
+                ChannelInfo {
+                    channel_id: channel_id(2),
+                    name: "Unterkanal".into(),
+                    description: None,
+                    parent_id: Some(channel_id(1)),
+                    sort_order: -1,
+                    max_clients: Some(8),
+                    current_clients: 0,
+                    password_protected: true,
+                    codec: "opus".into(),
+                    codec_quality: 5,
+                    has_children: true,
+                    child_count: 12,
+                    slow_mode_secs: 30,
+                    edit_window_secs: 900,
+                    join_by_approval: true,
+                },
+            ],
+            partial: true,
+        }),
+        ControlPayload::ChannelTreeExpand(ChannelTreeExpandRequest {
+            channel_id: channel_id(2),
+            depth: None,
+        }),
+        ControlPayload::ChannelJoin(ChannelJoinRequest {
+            channel_id: channel_id(2),
+            password: Some("pw".into()),
+            listen_only: true,
+        }),
+        ControlPayload::ChannelJoinResponse(ChannelJoinResponse {
+            channel_id: channel_id(1),
+            clients: vec![client_info(2, Some(channel_id(1)))],
+            member_count: 2,
+            members_partial: false,
+            listen_only: false,
+            speaking: vec![user_id(2)],
+            slow_mode_secs: 5,
+        }),
+        ControlPayload::ChannelMembers(ChannelMembersRequest {
+            channel_id: channel_id(1),
+            after: Some(user_id(1)),
+            limit: Some(100),
+        }),
+        ControlPayload::ChannelMembersResponse(ChannelMembersResponse {
+            channel_id: channel_id(1),
+            clients: vec![client_info(2, Some(channel_id(1)))],
+            total: 2,
+            next_after: Some(user_id(2)),
+        }),
+        ControlPayload::ChannelLeave(ChannelLeaveRequest {
+            channel_id: channel_id(1),
+        }),
+        ControlPayload::ChannelCreate(ChannelCreateRequest {
+            name: "Neu".into(),
+            description: None,
+            parent_id: Some(channel_id(1)),
+            password: None,
+            max_clients: Some(4),
+            sort_order: Some(3),
+        }),
+        ControlPayload::ChannelCreateResponse(ChannelCreateResponse {
+            channel_id: channel_id(3),
+        }),
+        ControlPayload::ChannelEdit(ChannelEditRequest {
+            channel_id: channel_id(3),
+            name: Some("Umbenannt".into()),
+            description: Some(String::new()),
+            password: None,
+            max_clients: None,
+            sort_order: Some(0),
+            slow_mode_secs: Some(10),
+            join_by_approval: Some(true),
+        }),
+        ControlPayload::ChannelEdited(ChannelEditedEvent {
+            channel: ChannelInfo {
+                channel_id: channel_id(3),
+                name: "Umbenannt".into(),
+                description: None,
+                parent_id: Some(channel_id(1)),
+                sort_order: 0,
+                max_clients: Some(4),
+                current_clients: 1,
+                password_protected: false,
+                codec: "opus".into(),
+                codec_quality: 7,
+                has_children: false,
+                child_count: 0,
+                slow_mode_secs: 10,
+                edit_window_secs: 0,
+                join_by_approval: true,
+            },
+        }),
+        ControlPayload::ChannelDelete(ChannelDeleteRequest {
+            channel_id: channel_id(3),
+            move_clients_to: Some(channel_id(1)),
+        }),
+        ControlPayload::ChannelTreeChanged(ChannelTreeChanged {
+            root_id: channel_id(4),
+            parent_id: Some(channel_id(1)),
+            created: vec![channel_id(4), channel_id(5)],
+        }),
+        ControlPayload::ChannelEmergencyMute(ChannelEmergencyMuteRequest {
+            channel_id: channel_id(1),
+            active: true,
+        }),
+        ControlPayload::ChannelEmergencyMuteEvent(ChannelEmergencyMuteEvent {
+            channel_id: channel_id(1),
+            active: true,
+            actor_id: Some(user_id(1)),
+            exempt: vec![user_id(1), user_id(3)],
+        }),
+        ControlPayload::ChannelInvite(ChannelInviteRequest {
+            channel_id: channel_id(1),
+            target_user_id: user_id(2),
+            message: Some("Kommst du kurz?".into()),
+        }),
+        ControlPayload::ChannelInviteResponse(ChannelInviteResponse {
+            invite_id: "e1000000-0000-4000-8000-000000000001".into(),
+            expires_in_secs: 120,
+        }),
+        ControlPayload::ChannelInviteReceived(ChannelInviteEvent {
+            invite_id: "e1000000-0000-4000-8000-000000000001".into(),
+            channel_id: channel_id(1),
+            channel_name: "Lobby".into(),
+            inviter_id: user_id(1),
+            inviter_name: "Benutzer 1".into(),
+            message: None,
+            expires_in_secs: 120,
+        }),
+        ControlPayload::ChannelInviteAnswer(ChannelInviteAnswerRequest {
+            invite_id: "e1000000-0000-4000-8000-000000000001".into(),
+            accept: true,
+            listen_only: false,
+        }),
+        ControlPayload::ChannelInviteResult(ChannelInviteResultEvent {
+            invite_id: "e1000000-0000-4000-8000-000000000001".into(),
+            channel_id: channel_id(1),
+            target_user_id: user_id(2),
+            outcome: ChannelInviteOutcome::Declined,
+        }),
+        ControlPayload::ChannelKnock(ChannelKnockRequest {
+            channel_id: channel_id(2),
+            message: None,
+        }),
+        ControlPayload::ChannelKnockResponse(ChannelKnockResponse {
+            knock_id: "e2000000-0000-4000-8000-000000000001".into(),
+            expires_in_secs: 120,
+        }),
+        ControlPayload::ChannelKnockReceived(ChannelKnockEvent {
+            knock_id: "e2000000-0000-4000-8000-000000000001".into(),
+            channel_id: channel_id(2),
+            requester_id: user_id(3),
+            requester_name: "Benutzer 3".into(),
+            message: Some("Darf ich rein?".into()),
+            expires_in_secs: 120,
+        }),
+        ControlPayload::ChannelKnockAnswer(ChannelKnockAnswerRequest {
+            knock_id: "e2000000-0000-4000-8000-000000000001".into(),
+            admit: true,
+        }),
+        ControlPayload::ChannelKnockResult(ChannelKnockResultEvent {
+            knock_id: "e2000000-0000-4000-8000-000000000001".into(),
+            channel_id: channel_id(2),
+            requester_id: user_id(3),
+            outcome: ChannelInviteOutcome::Accepted,
+            decided_by: Some(user_id(1)),
+        }),
+        ControlPayload::SoundboardPlay(SoundboardPlayRequest {
+            channel_id: channel_id(1),
+            sound_id: "5a000000-0000-4000-8000-000000000001".into(),
+        }),
+        ControlPayload::SoundboardStop(SoundboardStopRequest {
+            channel_id: channel_id(1),
+            playback_id: Some(user_id(9)),
+        }),
+        ControlPayload::SoundboardPlayback(SoundboardPlaybackEvent {
+            channel_id: channel_id(1),
+            sound_id: "5a000000-0000-4000-8000-000000000001".into(),
+            started_by: user_id(1),
+            client: ClientInfo {
+                user_id: user_id(9),
+                username: "soundboard".into(),
+                display_name: "Fanfare".into(),
+                channel_id: Some(channel_id(1)),
+                server_groups: Vec::new(),
+                is_muted: false,
+                is_deafened: false,
+                is_input_muted: false,
+                ssrc: Some(0x5B00),
+                listen_only: false,
+                soundboard: true,
+            },
+            active: true,
+        }),
+        ControlPayload::ClientList,
+        ControlPayload::ClientListResponse(ClientListResponse {
+            clients: vec![client_info(1, Some(channel_id(1))), client_info(2, None)],
+            state_version: 41,
+        }),
+        ControlPayload::ClientKick(ClientKickRequest {
+            target_user_id: user_id(2),
+            reason: Some("Spam".into()),
+            from_channel_only: true,
+            grace_secs: Some(30),
+        }),
+        ControlPayload::ClientBan(ClientBanRequest {
+            target_user_id: user_id(2),
+            reason: None,
+            duration_secs: Some(3600),
+            ban_ip: false,
+            remove_content_secs: Some(86400),
+        }),
+        ControlPayload::ClientMove(ClientMoveRequest {
+            target_user_id: user_id(2),
+            target_channel_id: channel_id(2),
+            reason: None,
+        }),
+        ControlPayload::ClientMoved(ClientMovedEvent {
+            user_id: user_id(2),
+            from_channel_id: Some(channel_id(1)),
+            to_channel_id: channel_id(3),
+            reason: Some("idle".into()),
+            state_version: 42,
+        }),
+        ControlPayload::ClientsMoveAll(ClientsMoveAllRequest {
+            from_channel_id: channel_id(1),
+            to_channel_id: channel_id(2),
+            only_user_ids: Some(vec![user_id(2), user_id(3)]),
+            allow_partial: true,
+            reason: Some("Event".into()),
+        }),
+        ControlPayload::ClientsMoveAllResponse(ClientsMoveAllResponse {
+            moved: vec![user_id(2)],
+            skipped: vec![SkippedMove {
+                user_id: user_id(3),
+                reason: MoveSkipReason::NotInChannel,
+            }],
+        }),
+        ControlPayload::ClientsMoved(ClientsMovedEvent {
+            user_ids: vec![user_id(2)],
+            from_channel_id: channel_id(1),
+            to_channel_id: channel_id(2),
+            reason: Some("Event".into()),
+            state_version: 43,
+        }),
+        ControlPayload::ClientConnected(ClientConnectedEvent {
+            client: client_info(4, None),
+            state_version: 44,
+        }),
+        ControlPayload::ClientDisconnected(ClientDisconnectedEvent {
+            client: client_info(4, None),
+            state_version: 47,
+        }),
+        ControlPayload::ClientJoinedChannel(ClientJoinedChannelEvent {
+            client: client_info(4, Some(channel_id(1))),
+            channel_id: channel_id(1),
+            state_version: 45,
+        }),
+        ControlPayload::ClientLeftChannel(ClientLeftChannelEvent {
+            client: client_info(4, None),
+            channel_id: channel_id(1),
+            state_version: 46,
+        }),
+        ControlPayload::ClientVoiceUpdated(ClientVoiceUpdatedEvent {
+            user_id: user_id(2),
+            channel_id: channel_id(1),
+            ssrc: Some(0x1002),
+            listen_only: false,
+        }),
+        ControlPayload::ClientSpeaking(ClientSpeakingEvent {
+            user_id: user_id(2),
+            channel_id: channel_id(1),
+            speaking: true,
+        }),
+        ControlPayload::ClientPoke(ClientPokeRequest {
+            target_user_id: user_id(2),
+            message: "Hallo \"du\" \u{2013} Umlaute: \u{e4}\u{f6}\u{fc}".into(),
+        }),
+        ControlPayload::ClientUpdate(ClientUpdateRequest {
+            display_name: None,
+            is_input_muted: Some(true),
+            is_output_muted: Some(false),
+            transmit_requested: Some(false),
+        }),
+        ControlPayload::ClientUpdated(ClientUpdatedEvent {
+            client: client_info(2, Some(channel_id(1))),
+        }),
+        ControlPayload::ResolveIds(ResolveIdsRequest {
+            user_ids: vec![user_id(2), user_id(9)],
+            channel_ids: vec![channel_id(1)],
+        }),
+        ControlPayload::ResolveIdsResponse(ResolveIdsResponse {
+            users: vec![
+                ResolvedUser {
+                    user_id: user_id(2),
+                    username: "user2".into(),
+                    display_name: "Benutzer 2".into(),
+                    deleted: false,
+                },
+                ResolvedUser {
+                    user_id: user_id(9),
+                    username: "[geloescht]".into(),
+                    display_name: "[geloescht]".into(),
+                    deleted: true,
+                },
+            ],
+            channels: vec![ResolvedChannel {
+                channel_id: channel_id(1),
+                name: "Lobby".into(),
+                deleted: false,
+            }],
+        }),
+        ControlPayload::ClientActivity,
+        ControlPayload::StateDiff(StateDiffRequest { since_version: 41 }),
+        ControlPayload::StateDiffResponse(StateDiffResponse {
+            current_version: 43,
+            snapshot_required: false,
+            events: vec![ControlMessage::new(
+                0,
+                ControlPayload::ClientMoved(ClientMovedEvent {
+                    user_id: user_id(3),
+                    from_channel_id: None,
+                    to_channel_id: channel_id(2),
+                    reason: None,
+                    state_version: 42,
+                }),
+            )
+            .to_json()
+            .expect("Ereignis serialisierbar")],
+        }),
+        ControlPayload::ServerInfo,
+        ControlPayload::ServerInfoResponse(ServerInfoResponse {
+            server_id: server_id(),
+            name: "Speakeasy".into(),
+            welcome_message: Some("Willkommen!".into()),
+            max_clients: 512,
+            current_clients: 3,
+            version: "0.1.0".into(),
+            uptime_secs: 86_400,
+            host_message: None,
+        }),
+        ControlPayload::ServerEdit(ServerEditRequest {
+            name: Some("Neuer Name".into()),
+            welcome_message: None,
+            max_clients: Some(64),
+            host_message: None,
+            afk_timeout_secs: Some(900),
+            afk_channel_id: Some(channel_id(3)),
+        }),
+        ControlPayload::ServerStop(ServerStopRequest {
+            reason: Some("Wartung".into()),
+            delay_secs: 0,
+            grace_secs: Some(300),
+        }),
+        ControlPayload::PendingDisconnectNotice(PendingDisconnectNotice {
+            action_id: "5f0c6a8e-1b2d-4c3e-9f4a-7b8c9d0e1f2a".into(),
+            kind: PendingDisconnectKind::ServerStop,
+            reason: Some("Wartung".into()),
+            seconds_remaining: 60,
+        }),
+        ControlPayload::CancelPendingDisconnect(CancelPendingDisconnectRequest {
+            action_id: "5f0c6a8e-1b2d-4c3e-9f4a-7b8c9d0e1f2a".into(),
+        }),
+        ControlPayload::PendingDisconnectCancelled(PendingDisconnectCancelledEvent {
+            action_id: "5f0c6a8e-1b2d-4c3e-9f4a-7b8c9d0e1f2a".into(),
+            kind: PendingDisconnectKind::ServerStop,
+        }),
+        ControlPayload::SessionCapture(SessionCaptureRequest {
+            user_id: user_id(2),
+            enabled: true,
+        }),
+        ControlPayload::SessionCaptureResponse(SessionCaptureResponse {
+            user_id: user_id(2),
+            enabled: true,
+            storage_disabled: false,
+        }),
+        ControlPayload::MotdChanged(MotdChangedEvent {
+            motd: Some(Motd {
+                markdown: "Neue Regeln: siehe [Wiki](https://example.org/regeln)".into(),
+                version: 4,
+            }),
+        }),
+        ControlPayload::ServerAnnouncement(ServerAnnouncement {
+            severity: AnnouncementSeverity::Critical,
+            title: "datenbank_nicht_erreichbar".into(),
+            message: "[kritisch] datenbank_nicht_erreichbar ausgeloest".into(),
+            resolved: false,
+        }),
+        ControlPayload::PermissionList {
+            target: "server_group:admin".into(),
+        },
+        ControlPayload::PermissionListResponse(PermissionListResponse {
+            target: "server_group:admin".into(),
+            permissions: vec![
+                PermissionEntry {
+                    permission: "b_channel_create".into(),
+                    value: PermissionValue::Grant,
+                },
+                PermissionEntry {
+                    permission: "b_client_kick".into(),
+                    value: PermissionValue::Deny,
+                },
+                PermissionEntry {
+                    permission: "b_server_stop".into(),
+                    value: PermissionValue::Skip,
+                },
+                PermissionEntry {
+                    permission: "i_channel_max_clients".into(),
+                    value: PermissionValue::IntLimit(-1),
+                },
+            ],
+        }),
+        ControlPayload::PermissionAdd(PermissionAddRequest {
+            target: "user:alice".into(),
+            permission: "i_upload_limit".into(),
+            value: PermissionValue::IntLimit(1024),
+        }),
+        ControlPayload::PermissionRemove(PermissionRemoveRequest {
+            target: "user:alice".into(),
+            permission: "i_upload_limit".into(),
+        }),
+        ControlPayload::EffectivePermissions(EffectivePermissionsRequest {
+            user_id: user_id(1),
+            channel_id: Some(channel_id(1)),
+        }),
+        ControlPayload::EffectivePermissionsResponse(EffectivePermissionsResponse {
+            user_id: user_id(1),
+            channel_id: Some(channel_id(1)),
+            permissions: vec![
+                EffectivePermissionEntry {
+                    permission: "b_channel_join".into(),
+                    value: None,
+                    source: None,
+                    scope: None,
+                },
+                EffectivePermissionEntry {
+                    permission: "i_upload_limit".into(),
+                    value: Some(PermissionValue::IntLimit(0)),
+                    source: Some("KanalGruppe".into()),
+                    scope: Some("kanal".into()),
+                },
+            ],
+        }),
+        ControlPayload::FileList {
+            channel_id: channel_id(1),
+        },
+        ControlPayload::FileListResponse(FileListResponse {
+            channel_id: channel_id(1),
+            files: vec![FileEntry {
+                file_id: "datei-1".into(),
+                name: "bericht.pdf".into(),
+                size_bytes: 4096,
+                channel_id: channel_id(1),
+                uploaded_by: user_id(1),
+                uploaded_at: 1_700_000_000,
+                mime_type: Some("application/pdf".into()),
+                access_count: 7,
+            }],
+        }),
+        ControlPayload::FileUpload(FileUploadRequest {
+            channel_id: channel_id(1),
+            filename: "bild.png".into(),
+            size_bytes: 123_456,
+            mime_type: None,
+            checksum: Some("e3b0c44298fc1c149afbf4c8996fb924".into()),
+        }),
+        ControlPayload::FileUploadResponse(FileUploadResponse {
+            file_id: "datei-2".into(),
+            upload_url: "https://example.invalid/upload/datei-2".into(),
+            expires_in_secs: 300,
+        }),
+        ControlPayload::FileDownload(FileDownloadRequest {
+            file_id: "datei-1".into(),
+        }),
+        ControlPayload::FileDownloadResponse(FileDownloadResponse {
+            file_id: "datei-1".into(),
+            filename: "bericht.pdf".into(),
+            download_url: "https://example.invalid/files/download/datei-1.abc".into(),
+            size_bytes: 4096,
+            sha256: "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855".into(),
+            expires_in_secs: 600,
+        }),
+        ControlPayload::FileDelete(FileDeleteRequest {
+            file_id: "datei-2".into(),
+        }),
+        ControlPayload::ChatSend(ChatSendRequest {
+            channel_id: channel_id(1),
+            content: "Hallo Welt\nZweite Zeile".into(),
+            reply_to: None,
+        }),
+        ControlPayload::ChatSendResponse(ChatSendResponse {
+            message_id: "nachricht-1".into(),
+            channel_id: channel_id(1),
+            created_at: 1_700_000_100,
+        }),
+        ControlPayload::ChatEdit(ChatEditRequest {
+            message_id: "nachricht-1".into(),
+            content: "Korrigiert".into(),
+        }),
+        ControlPayload::ChatDelete(ChatDeleteRequest {
+            message_id: "nachricht-1".into(),
+        }),
+        ControlPayload::ChatHistory(ChatHistoryRequest {
+            channel_id: channel_id(1),
+            before: Some("2023-11-14T22:13:20Z".into()),
+            limit: Some(50),
+            chunked: true,
+        }),
+        ControlPayload::ChatHistoryResponse(ChatHistoryResponse {
+            channel_id: channel_id(1),
+            messages: vec![ChatMessageInfo {
+                message_id: "nachricht-1".into(),
+                channel_id: channel_id(1),
+                sender_id: user_id(1),
+                content: "Hallo".into(),
+                message_type: "text".into(),
+                reply_to: None,
+                created_at: "2023-11-14T22:13:20Z".into(),
+                edited_at: Some("2023-11-14T22:15:00Z".into()),
+            }],
+        }),
+        ControlPayload::ChatHistoryChunk(ChatHistoryChunk {
+            channel_id: channel_id(1),
+            index: 0,
+            messages: vec![ChatMessageInfo {
+                message_id: "nachricht-1".into(),
+                channel_id: channel_id(1),
+                sender_id: user_id(1),
+                content: "Hallo".into(),
+                message_type: "text".into(),
+                reply_to: None,
+                created_at: "2023-11-14T22:13:20Z".into(),
+                edited_at: Some("2023-11-14T22:15:00Z".into()),
+            }],
+        }),
+        ControlPayload::ChatHistoryComplete(ChatHistoryComplete {
+            channel_id: channel_id(1),
+            chunks: 1,
+            total: 1,
+            next_before: Some("2023-11-14T22:13:20Z".into()),
+        }),
+        ControlPayload::ChatSearch(ChatSearchRequest {
+            channel_id: channel_id(1),
+            query: "100% sicher".into(),
+            limit: Some(20),
+            before: Some("2023-11-14T22:13:20Z".into()),
+            before_id: Some("nachricht-2".into()),
+        }),
+        ControlPayload::ChatSearchResponse(ChatSearchResponse {
+            channel_id: channel_id(1),
+            messages: vec![ChatMessageInfo {
+                message_id: "nachricht-1".into(),
+                channel_id: channel_id(1),
+                sender_id: user_id(1),
+                content: "Ist das 100% sicher?".into(),
+                message_type: "text".into(),
+                reply_to: None,
+                created_at: "2023-11-14T22:13:20Z".into(),
+                edited_at: None,
+            }],
+            next_before: Some("2023-11-14T22:13:20Z".into()),
+            next_before_id: Some("nachricht-1".into()),
+        }),
+        ControlPayload::ChatMessage(ChatMessageEvent {
+            message: ChatMessageInfo {
+                message_id: "nachricht-2".into(),
+                channel_id: channel_id(1),
+                sender_id: user_id(2),
+                content: "Antwort".into(),
+                message_type: "text".into(),
+                reply_to: Some("nachricht-1".into()),
+                created_at: "2023-11-14T22:16:00Z".into(),
+                edited_at: None,
+            },
+            sender_name: "Bob".into(),
+        }),
+        ControlPayload::ChatEdited(ChatEditedEvent {
+            message_id: "nachricht-1".into(),
+            channel_id: channel_id(1),
+            content: "Hallo zusammen".into(),
+            edited_at: "2023-11-14T22:15:00Z".into(),
+        }),
+        ControlPayload::ChatDeleted(ChatDeletedEvent {
+            message_id: "nachricht-2".into(),
+            channel_id: channel_id(1),
+        }),
+        ControlPayload::ChatBulkDeleted(ChatBulkDeletedEvent {
+            channel_id: channel_id(1),
+            message_ids: vec!["nachricht-3".into(), "nachricht-4".into()],
+        }),
+        ControlPayload::VoiceInit(VoiceInitRequest {
+            client_udp_port: 50_000,
+            preferred_codec: "opus".into(),
+            codec_negotiation: Some(CodecNegotiationRequest::fuer_preset(AudioPreset::Music)),
+            dtls_fingerprint: None,
+            force_new: true,
+            resequencing: true,
+            e2e_public_key: Some("AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA=".into()),
+        }),
+        ControlPayload::VoiceReady(VoiceReadyResponse {
+            server_udp_port: 9987,
+            server_ip: "192.0.2.1".into(),
+            ssrc: 0xCAFE_BABE,
+            codec: "opus".into(),
+            codec_negotiation: Some(CodecNegotiationResponse {
+                status: NegotiationStatus::Adjusted,
+                accepted: AudioPreset::Balanced.config(),
+                adjustment_reason: Some("Preset Musik nicht erlaubt".into()),
+                server_max_bitrate_kbps: 64,
+                preset: Some(AudioPreset::Balanced),
+            }),
+            server_dtls_fingerprint: Some("AA:BB:CC".into()),
+            crypto_mode: "dtls".into(),
+            resequencing: true,
+            hello_nonce: Some("00".repeat(32)),
+        }),
+        ControlPayload::VoiceDisconnect(VoiceDisconnectRequest { reason: None }),
+        ControlPayload::VoiceStats(VoiceStatsReport {
+            highest_sequence_sent: Some(499),
+            downlink: vec![SsrcReceiveStats {
+                ssrc: 0xCAFE_BABF,
+                received: 480,
+                expected: 500,
+            }],
+            rtt_ms: Some(48),
+            mos: Some(4.25),
+            playback_overflow: vec![SsrcOverflow {
+                ssrc: 0xCAFE_BABF,
+                overflow: 12,
+            }],
+        }),
+        ControlPayload::VoiceStatsResponse(VoiceStatsResponse {
+            uplink: SsrcReceiveStats {
+                ssrc: 0xCAFE_BABE,
+                received: 495,
+                expected: 500,
+            },
+            senders: vec![SsrcSender {
+                ssrc: 0xCAFE_BABF,
+                user_id: user_id(2),
+            }],
+            suppressed: vec![SsrcSuppressed {
+                ssrc: 0xCAFE_BABF,
+                suppressed: 25,
+            }],
+        }),
+        ControlPayload::VoiceQualityUpdate(VoiceQualityUpdate {
+            target_bitrate_kbps: 36,
+            reason: VoiceQualityReason::Critical,
+        }),
+        ControlPayload::E2EKeyRotationRequired(E2EKeyRotationRequiredEvent {
+            channel_id: channel_id(1),
+            epoch: 4,
+            reason: KeyRotationReason::MemberLeft,
+            members: vec![E2EMemberKey {
+                user_id: user_id(1),
+                public_key: "AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA=".into(),
+            }],
+        }),
+        ControlPayload::E2EKey(E2EKeyEnvelope {
+            channel_id: channel_id(1),
+            message: E2EKeyMessage::GroupKeyDistribute {
+                key_id: 9,
+                epoch: 4,
+                key_algorithm: AeadAlgorithm::Aes256Gcm,
+                purpose: KeyPurpose::Audio,
+                encrypted_keys: [(user_id(1).inner().to_string(), "d3JhcHBlZA==".to_string())]
+                    .into_iter()
+                    .collect(),
+                wrapping_algorithm: AeadAlgorithm::Aes256Gcm,
+                valid_from_ms: 1_700_000_000_000,
+                expires_at_ms: 0,
+            },
+        }),
+        ControlPayload::Ping(PingMessage {
+            timestamp_ms: 1_700_000_000_000,
+        }),
+        ControlPayload::Pong(PongMessage {
+            echo_timestamp_ms: 1_700_000_000_000,
+            server_timestamp_ms: 1_700_000_000_015,
+        }),
+        ControlPayload::Error(ErrorResponse {
+            code: ErrorCode::ChannelPasswordRequired,
+            message: "Passwort erforderlich".into(),
+            details: Some(serde_json::json!({ "channel_id": channel_id(2) })),
+        }),
+    ];
+
+    payloads
+        .into_iter()
+        .enumerate()
+        .map(|(i, payload)| ControlMessage::new(i as u32 + 1, payload))
+        .collect()
+}
+
+/// Voice-Vektoren: alle PacketTypes, Flag-Kombinationen und Grenzfaelle
+pub fn voice_vektoren() -> Vec<VoiceVektor> {
+    let ok = |name: String, paket: VoicePacket| VoiceVektor {
+        name,
+        hex: hex_kodieren(&paket.encode()),
+        erwartung: Erwartung::Ok,
+    };
+    let fehler = |name: &str, bytes: Vec<u8>| VoiceVektor {
+        name: name.into(),
+        hex: hex_kodieren(&bytes),
+        erwartung: Erwartung::Fehler,
+    };
+
+    let flags: [(&str, u16); 10] = [
+        ("keine", 0),
+        ("encrypted", VoiceFlags::ENCRYPTED),
+        ("fec", VoiceFlags::FEC),
+        ("dtx", VoiceFlags::DTX),
+        ("key_frame", VoiceFlags::KEY_FRAME),
+        ("speaking_start", VoiceFlags::SPEAKING_START),
+        ("speaking_stop", VoiceFlags::SPEAKING_STOP),
+        ("pcmu", VoiceFlags::PCMU),
+        ("encrypted_fec", VoiceFlags::ENCRYPTED | VoiceFlags::FEC),
+        (
+            "alle",
+            VoiceFlags::ENCRYPTED
+                | VoiceFlags::FEC
+                | VoiceFlags::DTX
+                | VoiceFlags::KEY_FRAME
+                | VoiceFlags::SPEAKING_START
+                | VoiceFlags::SPEAKING_STOP,
+        ),
+    ];
+    let typen = [
+        ("audio", PacketType::Audio),
+        ("silence", PacketType::Silence),
+        ("fec", PacketType::Fec),
+    ];
+
+    let mut vektoren = Vec::new();
+    for (typ_name, typ) in typen {
+        for (flag_name, flag) in flags {
+            let payload = match typ {
+                PacketType::Silence => Vec::new(),
+                _ => vec![0xF8, 0xFF, 0xFE],
+            };
+            vektoren.push(ok(
+                format!("v1_{typ_name}_flags_{flag_name}"),
+                VoicePacket {
+                    header: VoicePacketHeader::new(typ, flag, 42, 960, 0xCAFE_BABE),
+                    payload,
+                },
+            ));
+        }
+    }
+
+    // Grenzwerte der Header-Felder
+    vektoren.push(ok(
+        "v1_audio_maximale_feldwerte".into(),
+        VoicePacket {
+            header: VoicePacketHeader::new(PacketType::Audio, 0, u32::MAX, u32::MAX, u32::MAX),
+            payload: vec![0x00],
+        },
+    ));
+    vektoren.push(ok(
+        "v1_audio_maximale_nutzdaten".into(),
+        VoicePacket::neu_audio(1, 960, 1, vec![0xAB; MAX_NUTZDATEN_LAENGE]),
+    ));
+
+    // Ping: Anfrage und Antwort gleich gross
+    let ping = PingPaket::anfrage(3, 0x0102_0304);
+    for (name, paket) in [
+        ("v1_ping_anfrage", ping),
+        ("v1_ping_antwort", ping.beantworten(1_700_000_000_000)),
+    ] {
+        vektoren.push(VoiceVektor {
+            name: name.into(),
+            hex: hex_kodieren(&paket.encode()),
+            erwartung: Erwartung::Ok,
+        });
+    }
+
+    // Hello: Tag ueber SSRC und Versuch mit fester Nonce, Bestaetigung gleich gross
+    let hello = HelloPaket::neu(0xCAFE_BABE, 1, &[0x5A; 32]);
+    for (name, paket) in [
+        ("v1_hello_anfrage", hello),
+        ("v1_hello_bestaetigung", hello.bestaetigen()),
+    ] {
+        vektoren.push(VoiceVektor {
+            name: name.into(),
+            hex: hex_kodieren(&paket.encode()),
+            erwartung: Erwartung::Ok,
+        });
+    }
+
+    // Fehlerfaelle
+    let mut zu_gross = VoicePacketHeader::new(PacketType::Audio, 0, 1, 960, 1)
+        .encode()
+        .to_vec();
+    zu_gross.extend(vec![0xAB; MAX_NUTZDATEN_LAENGE + 1]);
+    vektoren.push(fehler("v1_audio_nutzdaten_zu_gross", zu_gross));
+
+    let header = VoicePacketHeader::new(PacketType::Audio, 0, 1, 960, 1).encode();
+    vektoren.push(fehler(
+        "header_zu_kurz",
+        header[..VoicePacketHeader::SIZE - 1].to_vec(),
+    ));
+
+    let mut unbekannter_typ = header;
+    unbekannter_typ[1] = 0xFF;
+    vektoren.push(fehler(
+        "v1_unbekannter_packet_type",
+        unbekannter_typ.to_vec(),
+    ));
+
+    // Zukuenftiger v2-Header: gleiches Layout, Version 2.
+    // Ein v1-Decoder muss ihn ablehnen statt ihn falsch zu interpretieren.
+    let mut v2 = VoicePacket::neu_audio(42, 960, 0xCAFE_BABE, vec![0xF8, 0xFF, 0xFE]).encode();
+    v2[0] = 2;
+    vektoren.push(fehler("v2_audio_von_v1_abgelehnt", v2));
+
+    vektoren
+}
+
+/// Frame-Codec-Vektoren (Laengenfeld u32 BE + JSON) mit `WIRE_MAX_FRAME_SIZE`
+pub fn wire_vektoren() -> Vec<WireVektor> {
+    let frame = |payload: &[u8]| -> Vec<u8> {
+        let mut buf = (payload.len() as u32).to_be_bytes().to_vec();
+        buf.extend_from_slice(payload);
+        buf
+    };
+    let vektor =
+        |name: &str, bytes: Vec<u8>, erwartung: Erwartung, nachrichten: usize| WireVektor {
+            name: name.into(),
+            hex: hex_kodieren(&bytes),
+            erwartung,
+            nachrichten,
+        };
+
+    let ping = ControlMessage::ping(1, 1_700_000_000_000)
+        .to_json()
+        .expect("Ping ist serialisierbar");
+    let pong = ControlMessage::pong(1, 1_700_000_000_000, 1_700_000_000_015)
+        .to_json()
+        .expect("Pong ist serialisierbar");
+
+    // Payload, der die maximale Frame-Groesse exakt ausschoepft
+    // (JSON erlaubt abschliessende Leerzeichen).
+    let mut maximal = ping.clone().into_bytes();
+    maximal.resize(WIRE_MAX_FRAME_SIZE, b' ');
+
+    let mut zwei = frame(ping.as_bytes());
+    zwei.extend(frame(pong.as_bytes()));
+
+    let ping_frame = frame(ping.as_bytes());
+
+    vec![
+        vektor("einzelner_ping", frame(ping.as_bytes()), Erwartung::Ok, 1),
+        vektor("zwei_frames_hintereinander", zwei, Erwartung::Ok, 2),
+        vektor("maximale_frame_groesse", frame(&maximal), Erwartung::Ok, 1),
+        vektor(
+            "frame_groesse_ueberschritten",
+            ((WIRE_MAX_FRAME_SIZE + 1) as u32).to_be_bytes().to_vec(),
+            Erwartung::Fehler,
+            0,
+        ),
+        vektor("leerer_payload", frame(&[]), Erwartung::Fehler, 0),
+        vektor(
+            "laengenfeld_unvollstaendig",
+            ping_frame[..LENGTH_FIELD_SIZE - 1].to_vec(),
+            Erwartung::Unvollstaendig,
+            0,
+        ),
+        vektor(
+            "payload_unvollstaendig",
+            ping_frame[..ping_frame.len() - 1].to_vec(),
+            Erwartung::Unvollstaendig,
+            0,
+        ),
+        vektor(
+            "ungueltiges_json",
+            frame(b"{\"request_id\":"),
+            Erwartung::Fehler,
+            0,
+        ),
+    ]
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn variantennamen_entsprechen_serde_tag() {
+        for msg in beispiel_nachrichten() {
+            let wert = serde_json::to_value(&msg).unwrap();
+            assert_eq!(wert["payload"]["type"], variante_name(&msg.payload));
+        }
+    }
+
+    #[test]
+    fn hex_round_trip() {
+        let bytes = vec![0x00, 0x7f, 0xff, 0x10];
+        assert_eq!(hex_kodieren(&bytes), "007fff10");
+        assert_eq!(hex_dekodieren("007fff10"), Some(bytes));
+        assert_eq!(hex_dekodieren("0"), None);
+        assert_eq!(hex_dekodieren("zz"), None);
+    }
+
+    #[test]
+    fn fingerabdruck_ist_stabil() {
+        let dateien = vec![("a.json", "{}".to_string())];
+        assert_eq!(fingerabdruck(&dateien), fingerabdruck(&dateien));
+        let anders = vec![("a.json", "{ }".to_string())];
+        assert_ne!(fingerabdruck(&dateien), fingerabdruck(&anders));
+    }
+}
@@ -9,8 +9,11 @@
 //! - Tagged Enums fuer typsichere Nachrichtentypen
 
 use serde::{Deserialize, Serialize};
+use speakeasy_core::error::{FehlerCode, SpeakeasyError};
 use speakeasy_core::types::{ChannelId, ServerId, UserId};
 
+use crate::codec::{CodecNegotiationRequest, CodecNegotiationResponse};
+
 // ---------------------------------------------------------------------------
 // Fehler-Codes
 // ---------------------------------------------------------------------------
@@ -37,10 +40,112 @@ pub enum ErrorCode {
     Banned,
 }
 
+/// Grobe Protokoll-Kategorie eines Taxonomie-Codes
+///
+/// Den feinen, stabilen Code transportiert `ErrorResponse::details`.
+impl From<FehlerCode> for ErrorCode {
+    fn from(code: FehlerCode) -> Self {
+        match code {
+            FehlerCode::UngueltigeAnmeldedaten | FehlerCode::TokenUngueltig => {
+                Self::InvalidCredentials
+            }
+            FehlerCode::SessionUngueltig => Self::SessionExpired,
+            FehlerCode::KontoGesperrt
+            | FehlerCode::ZugriffVerweigert
+            | FehlerCode::ScopeFehlt
+            | FehlerCode::RegistrierungDeaktiviert => Self::PermissionDenied,
+            FehlerCode::Gebannt => Self::Banned,
+            FehlerCode::BenutzerNichtGefunden
+            | FehlerCode::KanalNichtGefunden
+            | FehlerCode::NachrichtNichtGefunden
+            | FehlerCode::DateiNichtGefunden
+            | FehlerCode::NichtGefunden => Self::NotFound,
+            FehlerCode::KanalVoll => Self::ChannelFull,
+            FehlerCode::KanalPasswort => Self::ChannelPasswordRequired,
+            FehlerCode::ServerVoll => Self::ServerFull,
+            FehlerCode::RateLimit => Self::RateLimited,
+            FehlerCode::BenutzernameVergeben
+            | FehlerCode::EinladungUngueltig
+            | FehlerCode::DateiZuGross
+            | FehlerCode::KontingentErschoepft
+            | FehlerCode::Konflikt
+            | FehlerCode::UngueltigeAnfrage
+            | FehlerCode::ProtokollVersion
+            | FehlerCode::FristAbgelaufen => Self::InvalidRequest,
+            FehlerCode::Speicher
+            | FehlerCode::Codec
+            | FehlerCode::Krypto
+            | FehlerCode::Audio
+            | FehlerCode::Datenbank
+            | FehlerCode::Zeitlimit
+            | FehlerCode::Verbindung
+            | FehlerCode::Intern
+            | FehlerCode::Konfiguration
+            | FehlerCode::Plugin => Self::InternalError,
+        }
+    }
+}
+
+/// Naechstliegender Taxonomie-Code fuer Antworten ohne `details.code`
+impl From<ErrorCode> for FehlerCode {
+    fn from(code: ErrorCode) -> Self {
+        match code {
+            ErrorCode::InternalError => Self::Intern,
+            ErrorCode::InvalidRequest => Self::UngueltigeAnfrage,
+            ErrorCode::NotFound => Self::NichtGefunden,
+            ErrorCode::PermissionDenied => Self::ZugriffVerweigert,
+            ErrorCode::RateLimited => Self::RateLimit,
+            ErrorCode::InvalidCredentials => Self::UngueltigeAnmeldedaten,
+            ErrorCode::SessionExpired => Self::SessionUngueltig,
+            ErrorCode::AlreadyLoggedIn => Self::UngueltigeAnfrage,
+            ErrorCode::ChannelFull => Self::KanalVoll,
+            ErrorCode::ChannelPasswordRequired => Self::KanalPasswort,
+            ErrorCode::ServerFull => Self::ServerVoll,
+            ErrorCode::Banned => Self::Gebannt,
+        }
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Auth-Nachrichten
 // ---------------------------------------------------------------------------
 
+/// Begruessung des Clients vor dem Login
+///
+/// Erste Nachricht jeder Verbindung. Der Server antwortet mit `Welcome`,
+/// auch ohne Anmeldung; der Client entscheidet anhand der Antwort, ob er
+/// sich anmeldet (siehe [`crate::handshake`]).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HelloRequest {
+    /// Protokollversion des Clients
+    pub protocol_version: ProtokollVersion,
+    /// Vom Client unterstuetzte Faehigkeiten
+    pub capabilities: Vec<String>,
+    /// Name und Version der Client-Software (nur fuer Logs)
+    pub client_name: String,
+}
+
+/// Antwort des Servers auf `Hello`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WelcomeResponse {
+    /// Protokollversion des Servers
+    pub protocol_version: ProtokollVersion,
+    /// Aelteste Client-Protokollversion, die der Server bedient
+    pub min_client_version: ProtokollVersion,
+    /// Vom Server angebotene Faehigkeiten
+    pub capabilities: Vec<String>,
+    /// Version der Server-Software
+    pub server_version: String,
+    /// Hoechstlaenge einer Chat-Nachricht in Zeichen (fehlt bei Servern
+    /// vor 1.36, die ebenfalls [`MAX_NACHRICHT_ZEICHEN`] durchsetzen)
+    #[serde(default = "standard_max_nachricht")]
+    pub max_message_chars: u32,
+}
+
+fn standard_max_nachricht() -> u32 {
+    MAX_NACHRICHT_ZEICHEN as u32
+}
+
 /// Login-Anfrage vom Client
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LoginRequest {
@@ -54,6 +159,10 @@ pub struct LoginRequest {
     pub client_version: String,
     /// Anzeigename (kann vom Username abweichen)
     pub display_name: Option<String>,
+    /// Einwilligung in einen Sitzungsmitschnitt zur Fehlersuche; wirkt nur,
+    /// wenn ein Admin den Mitschnitt fuer diesen Benutzer freigegeben hat
+    #[serde(default)]
+    pub capture_consent: bool,
 }
 
 /// Erfolgreiche Login-Antwort
@@ -72,6 +181,48 @@ pub struct LoginResponse {
     /// Ob der Benutzer sein Passwort zwingend aendern muss (Standardpasswort noch aktiv)
     #[serde(default)]
     pub must_change_password: bool,
+    /// Aktuelle Willkommensnachricht des Servers
+    #[serde(default)]
+    pub welcome_message: Option<String>,
+    /// Nachricht des Tages (None = keine gesetzt)
+    #[serde(default)]
+    pub motd: Option<Motd>,
+}
+
+/// Registrierung eines neuen Kontos (nur vor dem Login)
+///
+/// Ob ein Einladungscode noetig ist, legt der Server fest. Abgelehnte
+/// Registrierungen tragen in `ErrorResponse::details` den Code
+/// `auth.registration_disabled` oder `auth.invite_invalid`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RegisterRequest {
+    /// Gewuenschter Benutzername
+    pub username: String,
+    /// Passwort (Klartext – wird serverseitig gehasht)
+    pub password: String,
+    /// Einladungscode (Pflicht wenn der Server nur mit Einladung registriert)
+    #[serde(default)]
+    pub invite_code: Option<String>,
+    /// Anzeigename fuer den ersten Login
+    #[serde(default)]
+    pub display_name: Option<String>,
+}
+
+/// Antwort auf `Register`
+///
+/// Der Client meldet sich anschliessend mit `Login` an.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RegisterResponse {
+    /// ID des neuen Kontos
+    pub user_id: UserId,
+    /// Benutzername des neuen Kontos
+    pub username: String,
+    /// Anzeigename aus der Anfrage
+    #[serde(default)]
+    pub display_name: Option<String>,
+    /// Ueber die Einladung zugewiesene Server-Gruppen
+    #[serde(default)]
+    pub server_groups: Vec<String>,
 }
 
 /// Logout-Anfrage (Client trennt Verbindung sauber)
@@ -86,6 +237,23 @@ pub struct LogoutResponse {
     pub success: bool,
 }
 
+/// Server -> Client: die Session laeuft bald ab
+///
+/// Der Client verlaengert sie mit `SessionRenew`; ohne Verlaengerung
+/// beantwortet der Server Anfragen nach Ablauf mit `SessionExpired`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionExpiryWarningEvent {
+    /// Verbleibende Gueltigkeit in Sekunden
+    pub expires_in_secs: u64,
+}
+
+/// Antwort auf `SessionRenew` (gleitender Ablauf ab der Verlaengerung)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionRenewResponse {
+    /// Neue Ablaufzeit des Session-Tokens (Unix-Timestamp)
+    pub expires_at: u64,
+}
+
 /// Passwort-Aenderungs-Anfrage
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PasswordChangeRequest {
@@ -128,6 +296,37 @@ pub struct SetAwayResponse {
     pub away: bool,
 }
 
+/// Datenexport-Antwort (das Archiv wird im Hintergrund erstellt)
+///
+/// Sobald es bereitsteht, folgt ein [`AccountExportReadyEvent`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccountDataExportResponse {
+    pub export_id: String,
+}
+
+/// Server -> Client: das Export-Archiv kann heruntergeladen werden
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccountExportReadyEvent {
+    pub export_id: String,
+    /// Einmal-Link (HTTP GET), verfaellt nach dem ersten Abruf
+    pub download_url: String,
+    /// Unix-Timestamp, ab dem der Link ungueltig ist
+    pub expires_at: i64,
+    pub size_bytes: u64,
+}
+
+/// Konto-Loeschanfrage (endgueltig, erfordert das aktuelle Passwort)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccountDeleteRequest {
+    pub password_confirmation: String,
+}
+
+/// Konto-Loeschbestaetigung (danach ist die Verbindung abgemeldet)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccountDeleteResponse {
+    pub success: bool,
+}
+
 // ---------------------------------------------------------------------------
 // Channel-Nachrichten
 // ---------------------------------------------------------------------------
@@ -145,12 +344,63 @@ pub struct ChannelInfo {
     pub password_protected: bool,
     pub codec: String,
     pub codec_quality: u8,
+    /// Es gibt Unterkanaele, die nicht in dieser Antwort enthalten sind
+    /// (per `ChannelTreeExpand` nachladen)
+    #[serde(default)]
+    pub has_children: bool,
+    /// Anzahl direkter Unterkanaele
+    #[serde(default)]
+    pub child_count: u32,
+    /// Mindestabstand zwischen zwei Chat-Nachrichten eines Benutzers in
+    /// Sekunden (0 = kein Langsam-Modus)
+    #[serde(default)]
+    pub slow_mode_secs: u32,
+    /// Wirksame Frist in Sekunden, in der Benutzer eigene Nachrichten
+    /// bearbeiten oder loeschen duerfen (0 = unbegrenzt); danach kann der
+    /// Client die Bearbeiten-Schaltflaeche abschalten
+    #[serde(default)]
+    pub edit_window_secs: u32,
+    /// Beitritt nur nach Freigabe durch ein Mitglied (per `ChannelKnock`)
+    #[serde(default)]
+    pub join_by_approval: bool,
 }
 
-/// Liste aller Kanaele
+/// Kanalliste anfordern
+///
+/// Ohne Parameter wird der ganze Baum geliefert. Ueberschreitet der Server
+/// seine Kanal-Schwelle, antwortet er stattdessen mit einem Teilbaum
+/// (Root-Ebene plus Pfad zum eigenen Kanal) und setzt `partial`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ChannelListRequest {
+    /// Nur Unterkanaele dieses Kanals (None = ab Server-Root)
+    #[serde(default)]
+    pub parent_id: Option<ChannelId>,
+    /// Anzahl der gelieferten Ebenen unterhalb von `parent_id`
+    /// (None = alle; der Server darf begrenzen)
+    #[serde(default)]
+    pub depth: Option<u32>,
+}
+
+/// Liste der Kanaele (ganzer Baum oder Teilbaum)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ChannelListResponse {
     pub channels: Vec<ChannelInfo>,
+    /// `true` wenn nur ein Teil des Baums enthalten ist; Clients fuehren die
+    /// Antwort dann mit ihrem Cache zusammen statt ihn zu ersetzen
+    #[serde(default)]
+    pub partial: bool,
+}
+
+/// Teilbaum unterhalb eines Kanals nachladen
+///
+/// Antwort ist eine `ChannelListResponse` mit `partial = true`, die den Kanal
+/// selbst und seine Unterkanaele bis `depth` Ebenen enthaelt.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChannelTreeExpandRequest {
+    pub channel_id: ChannelId,
+    /// Anzahl der Ebenen (None = 1)
+    #[serde(default)]
+    pub depth: Option<u32>,
 }
 
 /// Kanal beitreten
@@ -158,14 +408,70 @@ pub struct ChannelListResponse {
 pub struct ChannelJoinRequest {
     pub channel_id: ChannelId,
     pub password: Option<String>,
+    /// Nur zuhoeren: der Client sendet nie Audio, der Server verwirft
+    /// trotzdem eintreffende Pakete
+    #[serde(default)]
+    pub listen_only: bool,
 }
 
 /// Bestaetigung des Kanal-Beitritts
+///
+/// Ueberschreitet der Kanal die Mitglieder-Schwelle des Servers, enthaelt
+/// `clients` nur den eigenen Eintrag und die zuletzt aktiven Mitglieder und
+/// `members_partial` ist gesetzt. Die vollstaendige Liste laedt der Client
+/// dann seitenweise per `ChannelMembers` nach.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ChannelJoinResponse {
     pub channel_id: ChannelId,
-    /// Andere Clients im Kanal
+    /// Andere Clients im Kanal (bei `members_partial` eine Auswahl samt
+    /// eigenem Eintrag)
+    pub clients: Vec<ClientInfo>,
+    /// Anzahl aller Mitglieder einschliesslich des Beitretenden
+    #[serde(default)]
+    pub member_count: u32,
+    /// `clients` ist unvollstaendig (per `ChannelMembers` nachladen)
+    #[serde(default)]
+    pub members_partial: bool,
+    /// Effektiver Modus: auch ohne Anfrage gesetzt, wenn `b_voice_transmit`
+    /// im Kanal verweigert ist. Der Client oeffnet dann kein Mikrofon.
+    #[serde(default)]
+    pub listen_only: bool,
+    /// Mitglieder, die beim Beitritt gerade sprechen (danach `ClientSpeaking`)
+    #[serde(default)]
+    pub speaking: Vec<UserId>,
+    /// Langsam-Modus des Kanals in Sekunden (0 = aus); der Client sperrt
+    /// das Senden nach einer Nachricht entsprechend lange
+    #[serde(default)]
+    pub slow_mode_secs: u32,
+}
+
+/// Mitglieder eines Kanals seitenweise abrufen
+///
+/// Die Seiten sind nach User-ID sortiert; `after` ist die `next_after` der
+/// vorigen Antwort. Wer zwischen zwei Seiten beitritt oder geht, kann fehlen
+/// bzw. zusaetzlich erscheinen, niemand erscheint doppelt.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChannelMembersRequest {
+    pub channel_id: ChannelId,
+    /// Nur Mitglieder mit groesserer User-ID (None = erste Seite)
+    #[serde(default)]
+    pub after: Option<UserId>,
+    /// Hoechstzahl an Eintraegen (None = Server-Standard; der Server darf
+    /// begrenzen)
+    #[serde(default)]
+    pub limit: Option<u32>,
+}
+
+/// Eine Seite der Mitgliederliste
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChannelMembersResponse {
+    pub channel_id: ChannelId,
     pub clients: Vec<ClientInfo>,
+    /// Anzahl aller Mitglieder zum Zeitpunkt der Anfrage
+    pub total: u32,
+    /// Cursor fuer die naechste Seite (None = letzte Seite)
+    #[serde(default)]
+    pub next_after: Option<UserId>,
 }
 
 /// Kanal verlassen
@@ -200,6 +506,22 @@ pub struct ChannelEditRequest {
     pub password: Option<String>,
     pub max_clients: Option<u32>,
     pub sort_order: Option<i32>,
+    /// Langsam-Modus in Sekunden (0 = aus, None = unveraendert)
+    #[serde(default)]
+    pub slow_mode_secs: Option<u32>,
+    /// Beitritt nur nach Freigabe (None = unveraendert)
+    #[serde(default)]
+    pub join_by_approval: Option<bool>,
+}
+
+/// Server -> Client: Eigenschaften eines Kanals wurden geaendert
+///
+/// Geht an alle verbundenen Clients, die ihren Eintrag im Kanalbaum durch
+/// `channel` ersetzen.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChannelEditedEvent {
+    /// Der Kanal nach der Aenderung
+    pub channel: ChannelInfo,
 }
 
 /// Kanal loeschen
@@ -210,6 +532,217 @@ pub struct ChannelDeleteRequest {
     pub move_clients_to: Option<ChannelId>,
 }
 
+/// Server -> Client: Kanalbaum hat sich geaendert (z.B. nach Instanziierung
+/// einer Kanal-Vorlage). Clients sollen die Kanalliste neu laden.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChannelTreeChanged {
+    /// Wurzel des geaenderten Teilbaums
+    pub root_id: ChannelId,
+    /// Eltern-Kanal der Wurzel (None = Server-Root)
+    pub parent_id: Option<ChannelId>,
+    /// Alle neu angelegten Kanaele (inkl. Wurzel)
+    pub created: Vec<ChannelId>,
+}
+
+/// Notfall-Stummschaltung eines Kanals ein- oder ausschalten
+///
+/// Erfordert `b_channel_emergency_mute` im Kanal.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChannelEmergencyMuteRequest {
+    pub channel_id: ChannelId,
+    /// `true` = stumm schalten, `false` = aufheben
+    pub active: bool,
+}
+
+/// Server -> Client: Notfall-Stummschaltung eines Kanals hat sich geaendert
+///
+/// Geht an alle Kanalmitglieder, als Antwort an den Ausloeser und an
+/// Clients, die einem stumm geschalteten Kanal beitreten. Der Server
+/// verwirft die Sprachpakete aller nicht ausgenommenen Mitglieder; Clients
+/// sollen zusaetzlich ihre Aufnahme anhalten.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChannelEmergencyMuteEvent {
+    pub channel_id: ChannelId,
+    pub active: bool,
+    /// Ausloeser (None beim Beitritt in einen bereits stummen Kanal)
+    pub actor_id: Option<UserId>,
+    /// Mitglieder mit `b_channel_emergency_mute_bypass` (duerfen weiter senden)
+    #[serde(default)]
+    pub exempt: Vec<UserId>,
+}
+
+// ---------------------------------------------------------------------------
+// Einladungen und Beitrittsanfragen
+// ---------------------------------------------------------------------------
+
+/// Benutzer in den eigenen Kanal einladen
+///
+/// Erfordert `b_channel_invite` im Kanal; der Einladende muss selbst darin
+/// sein. Das Ziel muss online sein und erhaelt ein `ChannelInviteReceived`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChannelInviteRequest {
+    pub channel_id: ChannelId,
+    pub target_user_id: UserId,
+    #[serde(default)]
+    pub message: Option<String>,
+}
+
+/// Bestaetigung einer verschickten Einladung
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChannelInviteResponse {
+    pub invite_id: String,
+    /// Verfaellt nach so vielen Sekunden unbeantwortet
+    pub expires_in_secs: u64,
+}
+
+/// Server -> Client: Einladung in einen Kanal
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChannelInviteEvent {
+    pub invite_id: String,
+    pub channel_id: ChannelId,
+    pub channel_name: String,
+    pub inviter_id: UserId,
+    pub inviter_name: String,
+    #[serde(default)]
+    pub message: Option<String>,
+    pub expires_in_secs: u64,
+}
+
+/// Einladung annehmen oder ablehnen
+///
+/// Annehmen tritt dem Kanal sofort bei (Antwort `ChannelJoinResponse`);
+/// Kanal-Passwort und Freigabe entfallen. Ablehnen wird mit
+/// `ChannelInviteResult` bestaetigt.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChannelInviteAnswerRequest {
+    pub invite_id: String,
+    pub accept: bool,
+    /// Wie `ChannelJoinRequest::listen_only` (nur beim Annehmen)
+    #[serde(default)]
+    pub listen_only: bool,
+}
+
+/// Ausgang einer Einladung oder Beitrittsanfrage
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChannelInviteOutcome {
+    /// Einladung angenommen bzw. Anfragender eingelassen
+    Accepted,
+    /// Abgelehnt
+    Declined,
+    /// Vor der Antwort verfallen
+    Expired,
+}
+
+/// Server -> Client: Ausgang einer Einladung (an den Einladenden)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChannelInviteResultEvent {
+    pub invite_id: String,
+    pub channel_id: ChannelId,
+    pub target_user_id: UserId,
+    pub outcome: ChannelInviteOutcome,
+}
+
+/// Beitritt zu einem Kanal mit `join_by_approval` erbitten (Anklopfen)
+///
+/// Mitglieder mit `b_channel_join_approve` erhalten ein
+/// `ChannelKnockReceived`; ist keines online, wird die Anfrage abgelehnt.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChannelKnockRequest {
+    pub channel_id: ChannelId,
+    #[serde(default)]
+    pub message: Option<String>,
+}
+
+/// Bestaetigung einer gestellten Beitrittsanfrage
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChannelKnockResponse {
+    pub knock_id: String,
+    /// Verfaellt nach so vielen Sekunden unbeantwortet
+    pub expires_in_secs: u64,
+}
+
+/// Server -> Client: jemand bittet um Einlass (an die Freigebenden)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChannelKnockEvent {
+    pub knock_id: String,
+    pub channel_id: ChannelId,
+    pub requester_id: UserId,
+    pub requester_name: String,
+    #[serde(default)]
+    pub message: Option<String>,
+    pub expires_in_secs: u64,
+}
+
+/// Beitrittsanfrage annehmen oder ablehnen
+///
+/// Erfordert `b_channel_join_approve` im Kanal.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChannelKnockAnswerRequest {
+    pub knock_id: String,
+    pub admit: bool,
+}
+
+/// Server -> Client: Ausgang einer Beitrittsanfrage
+///
+/// Geht an den Anfragenden und alle Freigebenden des Kanals. Nach
+/// `Accepted` gelingt dem Anfragenden ein einzelner `ChannelJoin` ohne
+/// Passwort, solange die Anfrage gueltig gewesen waere.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChannelKnockResultEvent {
+    pub knock_id: String,
+    pub channel_id: ChannelId,
+    pub requester_id: UserId,
+    pub outcome: ChannelInviteOutcome,
+    /// Wer entschieden hat (None bei `Expired`)
+    #[serde(default)]
+    pub decided_by: Option<UserId>,
+}
+
+// ---------------------------------------------------------------------------
+// Soundboard
+// ---------------------------------------------------------------------------
+
+/// Soundboard-Clip im Kanal abspielen
+///
+/// Erfordert `b_soundboard_play` im Kanal. Der Server speist den Clip selbst
+/// ein; der Client muss dazu keine Voice-Verbindung haben.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SoundboardPlayRequest {
+    pub channel_id: ChannelId,
+    pub sound_id: String,
+}
+
+/// Soundboard-Wiedergabe anhalten
+///
+/// Ohne `playback_id` werden alle Wiedergaben im Kanal angehalten.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SoundboardStopRequest {
+    pub channel_id: ChannelId,
+    /// `client.user_id` aus dem `SoundboardPlaybackEvent`
+    #[serde(default)]
+    pub playback_id: Option<UserId>,
+}
+
+/// Server -> Client: Soundboard-Wiedergabe gestartet oder beendet
+///
+/// Geht an alle Kanalmitglieder und als Antwort an den Ausloeser. Die
+/// Wiedergabe erscheint als eigener Pseudo-Client (`client.soundboard`)
+/// mit eigener SSRC, damit Clients die Pakete zuordnen und getrennt regeln
+/// koennen. Wer waehrend einer Wiedergabe beitritt, findet den Pseudo-Client
+/// in der `ChannelJoinResponse`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SoundboardPlaybackEvent {
+    pub channel_id: ChannelId,
+    pub sound_id: String,
+    /// Wer die Wiedergabe gestartet hat
+    pub started_by: UserId,
+    /// Pseudo-Client der Wiedergabe (`user_id` = Wiedergabe-ID)
+    pub client: ClientInfo,
+    /// `false` = Wiedergabe beendet oder angehalten
+    pub active: bool,
+}
+
 // ---------------------------------------------------------------------------
 // Client-Nachrichten
 // ---------------------------------------------------------------------------
@@ -225,12 +758,27 @@ pub struct ClientInfo {
     pub is_muted: bool,
     pub is_deafened: bool,
     pub is_input_muted: bool,
+    /// Aktuelle Voice-SSRC (None = keine Voice-Verbindung)
+    #[serde(default)]
+    pub ssrc: Option<u32>,
+    /// Nur Zuhoerer (kann nicht senden)
+    #[serde(default)]
+    pub listen_only: bool,
+    /// Pseudo-Client einer Soundboard-Wiedergabe
+    #[serde(default)]
+    pub soundboard: bool,
 }
 
 /// Liste aller verbundenen Clients
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ClientListResponse {
     pub clients: Vec<ClientInfo>,
+    /// Zustandsversion des Abbilds (Ausgangspunkt fuer `StateDiff`)
+    ///
+    /// Wird vor dem Abbild gelesen: Ereignisse ab dieser Version koennen
+    /// bereits enthalten sein und werden beim Nachholen erneut angewendet.
+    #[serde(default)]
+    pub state_version: u64,
 }
 
 /// Client kicken
@@ -239,6 +787,10 @@ pub struct ClientKickRequest {
     pub target_user_id: UserId,
     pub reason: Option<String>,
     pub from_channel_only: bool,
+    /// Frist in Sekunden bis zum Kick; der Betroffene erhaelt solange einen
+    /// Countdown (`PendingDisconnectNotice`). `None`/0 = sofort
+    #[serde(default)]
+    pub grace_secs: Option<u32>,
 }
 
 /// Client bannen
@@ -249,6 +801,10 @@ pub struct ClientBanRequest {
     /// Bann-Dauer in Sekunden (None = dauerhaft)
     pub duration_secs: Option<u64>,
     pub ban_ip: bool,
+    /// Nachrichten und Dateien des Gebannten aus den letzten Sekunden
+    /// entfernen (None = Inhalte bleiben)
+    #[serde(default)]
+    pub remove_content_secs: Option<u64>,
 }
 
 /// Client in anderen Kanal verschieben
@@ -259,19 +815,267 @@ pub struct ClientMoveRequest {
     pub reason: Option<String>,
 }
 
-/// Client anklopfen (Poke)
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct ClientPokeRequest {
-    pub target_user_id: UserId,
-    pub message: String,
+/// Server -> Client: ein Client wurde in einen anderen Kanal verschoben
+///
+/// Wird an alle Clients gesendet, sowohl bei manuellen Moves als auch bei
+/// automatischen (z.B. `reason = "idle"` durch die AFK-Erkennung).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClientMovedEvent {
+    pub user_id: UserId,
+    pub from_channel_id: Option<ChannelId>,
+    pub to_channel_id: ChannelId,
+    pub reason: Option<String>,
+    /// Zustandsversion nach diesem Ereignis (0 = Server ohne Versionierung)
+    #[serde(default)]
+    pub state_version: u64,
+}
+
+/// Alle (oder ausgewaehlte) Clients eines Kanals gemeinsam verschieben
+///
+/// Der Server arbeitet auf einer Momentaufnahme der Kanalmitglieder zum
+/// Zeitpunkt der Anfrage. Wer den Kanal bis zur Ausfuehrung verlassen hat,
+/// wird uebersprungen und in der Antwort gemeldet.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClientsMoveAllRequest {
+    pub from_channel_id: ChannelId,
+    pub to_channel_id: ChannelId,
+    /// Nur diese Benutzer verschieben (None = alle im Quellkanal)
+    pub only_user_ids: Option<Vec<UserId>>,
+    /// Reicht der Platz im Zielkanal nicht, so viele wie moeglich verschieben
+    /// statt die gesamte Anfrage mit `ChannelFull` abzulehnen
+    #[serde(default)]
+    pub allow_partial: bool,
+    pub reason: Option<String>,
+}
+
+/// Grund, warum ein Benutzer beim Sammel-Move uebersprungen wurde
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MoveSkipReason {
+    /// Benutzer war (nicht mehr) im Quellkanal
+    NotInChannel,
+    /// Move-Power des Moderators kleiner als die benoetigte Power des Ziels
+    InsufficientPower,
+    /// Zielkanal voll (nur bei `allow_partial`)
+    ChannelFull,
+}
+
+/// Uebersprungener Benutzer beim Sammel-Move
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SkippedMove {
+    pub user_id: UserId,
+    pub reason: MoveSkipReason,
+}
+
+/// Antwort auf `ClientsMoveAll`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClientsMoveAllResponse {
+    pub moved: Vec<UserId>,
+    pub skipped: Vec<SkippedMove>,
+}
+
+/// Server -> Client: mehrere Clients wurden gemeinsam verschoben
+///
+/// Ersetzt beim Sammel-Move die einzelnen `ClientMoved`-Ereignisse.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClientsMovedEvent {
+    pub user_ids: Vec<UserId>,
+    pub from_channel_id: ChannelId,
+    pub to_channel_id: ChannelId,
+    pub reason: Option<String>,
+    /// Zustandsversion nach diesem Ereignis (0 = Server ohne Versionierung)
+    #[serde(default)]
+    pub state_version: u64,
+}
+
+/// Server -> Client: ein Client hat sich angemeldet
+///
+/// Geht an alle anderen Clients; der Angemeldete kennt sich aus der
+/// Login-Antwort. Ein anschliessender Beitritt zum Standard-Kanal folgt
+/// als eigenes `ClientJoinedChannel`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClientConnectedEvent {
+    pub client: ClientInfo,
+    /// Zustandsversion nach diesem Ereignis (0 = Server ohne Versionierung)
+    #[serde(default)]
+    pub state_version: u64,
+}
+
+/// Server -> Client: ein Client ist nicht mehr verbunden
+///
+/// Logout, Kick oder abgebrochene Verbindung. War der Client in einem
+/// Kanal, kommt vorher `ClientLeftChannel`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClientDisconnectedEvent {
+    pub client: ClientInfo,
+    /// Zustandsversion nach diesem Ereignis (0 = Server ohne Versionierung)
+    #[serde(default)]
+    pub state_version: u64,
+}
+
+/// Server -> Client: ein Client ist einem Kanal beigetreten
+///
+/// Geht an alle ausser dem Beitretenden (der hat die `ChannelJoinResponse`).
+/// Verschiebungen melden weiterhin `ClientMoved`/`ClientsMoved`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClientJoinedChannelEvent {
+    pub client: ClientInfo,
+    pub channel_id: ChannelId,
+    /// Zustandsversion nach diesem Ereignis (0 = Server ohne Versionierung)
+    #[serde(default)]
+    pub state_version: u64,
+}
+
+/// Server -> Client: ein Client hat einen Kanal verlassen
+///
+/// Geht an alle ausser dem Verlassenden.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClientLeftChannelEvent {
+    pub client: ClientInfo,
+    pub channel_id: ChannelId,
+    /// Zustandsversion nach diesem Ereignis (0 = Server ohne Versionierung)
+    #[serde(default)]
+    pub state_version: u64,
+}
+
+/// Verpasste Presence-Ereignisse seit einer Zustandsversion nachholen
+///
+/// Fuer kurze Luecken (Standby, Wiederaufnahme der Sitzung) statt einer
+/// neuen `ClientList`. `since_version` stammt aus `ClientListResponse`
+/// oder dem letzten empfangenen Ereignis.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StateDiffRequest {
+    pub since_version: u64,
+}
+
+/// Antwort auf `StateDiff`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StateDiffResponse {
+    /// Aktuelle Zustandsversion des Servers
+    pub current_version: u64,
+    /// `true`: die Luecke ist nicht mehr vollstaendig gespeichert, der
+    /// Client muss `ClientList` neu laden (`events` ist dann leer)
+    pub snapshot_required: bool,
+    /// Ereignisse nach `since_version` in Versandreihenfolge, jeweils die
+    /// serialisierte `ControlMessage` wie sie gesendet wurde
+    #[serde(default)]
+    pub events: Vec<String>,
+}
+
+impl StateDiffResponse {
+    /// Dekodiert die nachgeholten Ereignisse
+    pub fn ereignisse(&self) -> Result<Vec<ControlMessage>, serde_json::Error> {
+        self.events
+            .iter()
+            .map(|e| ControlMessage::from_json(e))
+            .collect()
+    }
+}
+
+/// Server -> Client: Voice-SSRC eines Kanalmitglieds hat sich geaendert
+///
+/// Zusammen mit `ClientInfo::ssrc` in `ChannelJoinResponse` die einzige
+/// Quelle fuer die SSRC->Benutzer-Zuordnung der Clients. Wird an die
+/// uebrigen Mitglieder des Kanals gesendet, wenn ein Mitglied eine neue SSRC
+/// erhaelt oder den Kanal bzw. Voice verlaesst (`ssrc = None`). Der Server
+/// garantiert, dass eine SSRC zu jedem Zeitpunkt hoechstens einem Benutzer
+/// gehoert. Auch ein Wechsel zwischen Senden und Nur-Zuhoeren wird so
+/// gemeldet.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClientVoiceUpdatedEvent {
+    pub user_id: UserId,
+    pub channel_id: ChannelId,
+    pub ssrc: Option<u32>,
+    /// Nur Zuhoerer (kann nicht senden)
+    #[serde(default)]
+    pub listen_only: bool,
+}
+
+/// Server -> Client: ein Kanalmitglied beginnt oder beendet eine Sprechphase
+///
+/// Abgeleitet aus den Sprachpaketen am Server (Speaking-Flags und
+/// Paketaktivitaet mit Nachlauf), also auch fuer Mitglieder, deren Pakete
+/// der Client selbst nicht empfaengt. Pro Benutzer hoechstens ein Wechsel
+/// alle 300 ms; schnelles Flattern wird zum letzten Zustand zusammengefasst.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClientSpeakingEvent {
+    pub user_id: UserId,
+    pub channel_id: ChannelId,
+    pub speaking: bool,
+}
+
+/// Client anklopfen (Poke)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClientPokeRequest {
+    pub target_user_id: UserId,
+    pub message: String,
+}
+
+/// Eigene Client-Informationen aktualisieren
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClientUpdateRequest {
+    pub display_name: Option<String>,
+    pub is_input_muted: Option<bool>,
+    pub is_output_muted: Option<bool>,
+    /// Wechsel zwischen Senden (`true`) und Nur-Zuhoeren (`false`) ohne den
+    /// Kanal zu verlassen. Senden erfordert `b_voice_transmit` im Kanal.
+    #[serde(default)]
+    pub transmit_requested: Option<bool>,
+}
+
+/// Server -> Client: Name eines verbundenen Clients hat sich geaendert
+///
+/// Geht an alle verbundenen Clients (auch den Aendernden), die
+/// zwischengespeicherte Namen durch `client` ersetzen.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClientUpdatedEvent {
+    /// Der Client nach der Aenderung
+    pub client: ClientInfo,
+}
+
+/// Hoechstzahl IDs (Benutzer und Kanaele zusammen) pro `ResolveIds`
+pub const MAX_AUFLOESUNG_IDS: usize = 100;
+
+/// Namen zu Benutzer- und Kanal-IDs nachschlagen
+///
+/// Fuer IDs, die der Client nicht aus Snapshot oder Ereignissen kennt (z.B.
+/// Absender alter Nachrichten, die nicht mehr verbunden sind). Mehr als
+/// [`MAX_AUFLOESUNG_IDS`] IDs lehnt der Server mit `InvalidRequest` ab.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ResolveIdsRequest {
+    #[serde(default)]
+    pub user_ids: Vec<UserId>,
+    #[serde(default)]
+    pub channel_ids: Vec<ChannelId>,
+}
+
+/// Aufgeloester Benutzer
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ResolvedUser {
+    pub user_id: UserId,
+    pub username: String,
+    /// Anzeigename (bei nicht verbundenen Benutzern gleich `username`)
+    pub display_name: String,
+    /// Konto existiert nicht mehr; die Namen sind ein Platzhalter
+    #[serde(default)]
+    pub deleted: bool,
+}
+
+/// Aufgeloester Kanal
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ResolvedChannel {
+    pub channel_id: ChannelId,
+    pub name: String,
+    /// Kanal existiert nicht mehr; der Name ist ein Platzhalter
+    #[serde(default)]
+    pub deleted: bool,
 }
 
-/// Eigene Client-Informationen aktualisieren
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct ClientUpdateRequest {
-    pub display_name: Option<String>,
-    pub is_input_muted: Option<bool>,
-    pub is_output_muted: Option<bool>,
+/// Antwort auf `ResolveIds` (ein Eintrag je angefragter ID, ohne Duplikate)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ResolveIdsResponse {
+    pub users: Vec<ResolvedUser>,
+    pub channels: Vec<ResolvedChannel>,
 }
 
 // ---------------------------------------------------------------------------
@@ -298,6 +1102,12 @@ pub struct ServerEditRequest {
     pub welcome_message: Option<String>,
     pub max_clients: Option<u32>,
     pub host_message: Option<String>,
+    /// Inaktivitaet in Sekunden bis zur Verschiebung in den AFK-Kanal (0 = aus)
+    #[serde(default)]
+    pub afk_timeout_secs: Option<u32>,
+    /// Ziel-Kanal fuer inaktive Clients
+    #[serde(default)]
+    pub afk_channel_id: Option<ChannelId>,
 }
 
 /// Server herunterfahren (Admin)
@@ -306,6 +1116,117 @@ pub struct ServerStopRequest {
     pub reason: Option<String>,
     /// Verzoegerung in Sekunden bevor der Server stoppt
     pub delay_secs: u32,
+    /// Frist in Sekunden mit Countdown an alle Clients, abbrechbar ueber
+    /// `CancelPendingDisconnect`. Ersetzt `delay_secs`, wenn groesser 0
+    #[serde(default)]
+    pub grace_secs: Option<u32>,
+}
+
+/// Hoechste Frist fuer angekuendigte Kicks und Server-Stopps
+pub const MAX_TRENNUNGSFRIST_SEK: u32 = 3600;
+
+/// Art einer angekuendigten Trennung
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PendingDisconnectKind {
+    /// Kick vom Server
+    Kick,
+    /// Kick nur aus dem aktuellen Kanal
+    ChannelKick,
+    ServerStop,
+}
+
+/// Server -> Client: Countdown bis zu einer angekuendigten Trennung
+///
+/// Kommt sofort und erneut an einigen Meldepunkten vor Ablauf. Wer die
+/// Trennung ausgeloest hat, erhaelt dieselbe Meldung als Antwort und kann
+/// sie ueber `action_id` abbrechen.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PendingDisconnectNotice {
+    pub action_id: String,
+    pub kind: PendingDisconnectKind,
+    pub reason: Option<String>,
+    pub seconds_remaining: u32,
+}
+
+/// Angekuendigte Trennung abbrechen (Admin)
+///
+/// Erfordert dieselbe Berechtigung wie das Ausloesen.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CancelPendingDisconnectRequest {
+    pub action_id: String,
+}
+
+/// Server -> Client: eine angekuendigte Trennung findet nicht statt
+///
+/// Geht an die Betroffenen und als Antwort an den Abbrechenden.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PendingDisconnectCancelledEvent {
+    pub action_id: String,
+    pub kind: PendingDisconnectKind,
+}
+
+/// Sitzungsmitschnitt fuer einen Benutzer freigeben oder beenden (Admin)
+///
+/// Mitgeschnitten wird erst ab dem naechsten Login des Benutzers und nur,
+/// wenn dieser dabei `capture_consent` setzt. Erfordert `b_server_modify`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionCaptureRequest {
+    pub user_id: UserId,
+    pub enabled: bool,
+}
+
+/// Antwort auf `SessionCapture`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionCaptureResponse {
+    pub user_id: UserId,
+    pub enabled: bool,
+    /// Der Server hat kein Mitschnitt-Verzeichnis konfiguriert; die
+    /// Freigabe wird gespeichert, schreibt aber nichts
+    #[serde(default)]
+    pub storage_disabled: bool,
+}
+
+/// Nachricht des Tages (Markdown-Teilmenge, vom Server bereinigt)
+///
+/// `version` steigt bei jeder inhaltlichen Aenderung. Clients merken sich die
+/// zuletzt bestaetigte Version und zeigen die Nachricht erst nach einer
+/// Aenderung erneut an.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Motd {
+    pub markdown: String,
+    pub version: u64,
+}
+
+/// Server -> Client: die Nachricht des Tages wurde geaendert
+///
+/// Eine leere Nachricht (`motd = None`) bedeutet, dass sie entfernt wurde.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MotdChangedEvent {
+    pub motd: Option<Motd>,
+}
+
+/// Dringlichkeit einer Server-Ankuendigung
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AnnouncementSeverity {
+    Info,
+    Warning,
+    Critical,
+}
+
+/// Server -> Client: Hinweis des Servers, z.B. ein Betriebsalarm
+///
+/// Geht nur an Clients mit passender Berechtigung (bei Alarmen:
+/// Administratoren); `resolved` markiert die Entwarnung zu einem frueheren
+/// Hinweis mit demselben `title`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ServerAnnouncement {
+    pub severity: AnnouncementSeverity,
+    pub title: String,
+    pub message: String,
+    #[serde(default)]
+    pub resolved: bool,
 }
 
 // ---------------------------------------------------------------------------
@@ -351,6 +1272,36 @@ pub struct PermissionRemoveRequest {
     pub permission: String,
 }
 
+/// Effektive Berechtigungen eines Benutzers abfragen (Diagnose)
+///
+/// Erfordert `b_permission_view`. Ohne `channel_id` gilt der Server-Root.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EffectivePermissionsRequest {
+    pub user_id: UserId,
+    #[serde(default)]
+    pub channel_id: Option<ChannelId>,
+}
+
+/// Aufgeloeste Berechtigung mit der Regel, die entschieden hat
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EffectivePermissionEntry {
+    pub permission: String,
+    /// `None` = auf keiner Ebene gesetzt (Standard: erlaubt)
+    pub value: Option<PermissionValue>,
+    /// Entscheidende Stufe, z.B. `KanalGruppe` oder `ServerGruppe(Moderator)`
+    pub source: Option<String>,
+    /// Geltungsbereich der entscheidenden Regel: `kanal` oder `server`
+    pub scope: Option<String>,
+}
+
+/// Effektive Berechtigungen fuer jeden Key des Berechtigungskatalogs
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EffectivePermissionsResponse {
+    pub user_id: UserId,
+    pub channel_id: Option<ChannelId>,
+    pub permissions: Vec<EffectivePermissionEntry>,
+}
+
 // ---------------------------------------------------------------------------
 // File-Nachrichten
 // ---------------------------------------------------------------------------
@@ -365,6 +1316,9 @@ pub struct FileEntry {
     pub uploaded_by: UserId,
     pub uploaded_at: u64,
     pub mime_type: Option<String>,
+    /// Anzahl protokollierter Downloads
+    #[serde(default)]
+    pub access_count: u64,
 }
 
 /// Dateiliste eines Kanals
@@ -395,6 +1349,26 @@ pub struct FileUploadResponse {
     pub expires_in_secs: u64,
 }
 
+/// Download-Link fuer eine Datei anfordern
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileDownloadRequest {
+    pub file_id: String,
+}
+
+/// Signierter Download-Link (HTTP GET, ohne weitere Anmeldung)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileDownloadResponse {
+    pub file_id: String,
+    pub filename: String,
+    pub download_url: String,
+    /// Erwartete Groesse in Bytes
+    pub size_bytes: u64,
+    /// Erwartete Checksum (SHA-256 hex)
+    pub sha256: String,
+    /// Link-Gueltigkeit in Sekunden
+    pub expires_in_secs: u64,
+}
+
 /// Datei loeschen
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FileDeleteRequest {
@@ -408,12 +1382,32 @@ pub struct FileDeleteRequest {
 /// Voice-Verbindung initialisieren (UDP Port Negotiation)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct VoiceInitRequest {
-    /// UDP-Port des Clients (fuer STUN/ICE)
+    /// UDP-Port des Clients (nur informativ; die Adresse lernt der Server
+    /// aus dem Hello, siehe `VoiceReadyResponse::hello_nonce`)
     pub client_udp_port: u16,
     /// Bevorzugter Codec (wird bestaetigt oder abgelehnt)
     pub preferred_codec: String,
+    /// Gewuenschte Opus-Konfiguration. Der Server begrenzt sie auf seine
+    /// Richtlinie und meldet das Ergebnis in
+    /// `VoiceReadyResponse::codec_negotiation` (ohne: Server-Standard).
+    #[serde(default)]
+    pub codec_negotiation: Option<CodecNegotiationRequest>,
     /// DTLS-Fingerprint des Clients (fuer DTLS-Handshake)
     pub dtls_fingerprint: Option<String>,
+    /// Neue Sitzung erzwingen: eine bestehende Voice-Registrierung wird
+    /// abgebaut und eine neue SSRC vergeben. Ohne das Flag bestaetigt ein
+    /// wiederholter VoiceInit die bestehende Sitzung.
+    #[serde(default)]
+    pub force_new: bool,
+    /// Client kann Pakete mit vom Server umgeschriebener Sequenznummer und
+    /// umgeschriebenem Zeitstempel verarbeiten (Resequenzierung je Empfaenger)
+    #[serde(default)]
+    pub resequencing: bool,
+    /// Oeffentlicher X25519-Schluessel des Clients (Base64) fuer die
+    /// Verteilung der E2E-Gruppenschluessel. Ohne ihn bleibt der Client im
+    /// E2E-Modus von der Schluesselverteilung ausgeschlossen.
+    #[serde(default)]
+    pub e2e_public_key: Option<String>,
 }
 
 /// Voice-Setup Bestaetigung vom Server
@@ -427,10 +1421,26 @@ pub struct VoiceReadyResponse {
     pub ssrc: u32,
     /// Akzeptierter Codec
     pub codec: String,
+    /// Vereinbarte Opus-Konfiguration, falls der Client eine angefragt hat
+    /// und der Codec Opus ist; Encoder und Decoder des Clients folgen ihr
+    #[serde(default)]
+    pub codec_negotiation: Option<CodecNegotiationResponse>,
     /// DTLS-Fingerprint des Servers
     pub server_dtls_fingerprint: Option<String>,
     /// Krypto-Modus
     pub crypto_mode: String,
+    /// Der Server schreibt Sequenznummern weitergeleiteter Pakete um, sodass
+    /// serverseitig verworfene Pakete keine Luecken hinterlassen. Ohne
+    /// Resequenzierung (z.B. bei E2E) meldet er verworfene Pakete stattdessen
+    /// in `VoiceStatsResponse::suppressed`.
+    #[serde(default)]
+    pub resequencing: bool,
+    /// Nonce (hex) fuer das Hello-Paket. Der Server leitet erst Pakete
+    /// an/von einer UDP-Adresse weiter, nachdem sich der Client von dort mit
+    /// einem Hello gemeldet hat, dessen HMAC mit dieser Nonce geschluesselt
+    /// ist (siehe [`crate::voice::HelloPaket`]).
+    #[serde(default)]
+    pub hello_nonce: Option<String>,
 }
 
 /// Voice-Verbindung trennen
@@ -439,10 +1449,152 @@ pub struct VoiceDisconnectRequest {
     pub reason: Option<String>,
 }
 
+/// Empfangsstatistik fuer den Paketstrom einer SSRC
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SsrcReceiveStats {
+    pub ssrc: u32,
+    /// Empfangene Pakete (kumuliert)
+    pub received: u64,
+    /// Erwartete Pakete laut Sequenznummern (kumuliert)
+    pub expected: u64,
+}
+
+/// Periodischer Qualitaetsbericht des Clients (Downlink-Sicht)
+///
+/// Enthaelt je empfangener SSRC, wie viele Pakete beim Client angekommen sind.
+/// Der Server antwortet mit `VoiceStatsResponse` (Uplink-Sicht).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VoiceStatsReport {
+    /// Hoechste vom Client gesendete Sequenznummer
+    pub highest_sequence_sent: Option<u32>,
+    /// Empfangsstatistik je entfernter SSRC
+    pub downlink: Vec<SsrcReceiveStats>,
+    /// Vom Client gemessene Round-Trip-Time zum Server (ms)
+    #[serde(default)]
+    pub rtt_ms: Option<u32>,
+    /// Vom Client geschaetzte Sprachqualitaet der eigenen Sitzung (1.0–4.5)
+    #[serde(default)]
+    pub mos: Option<f32>,
+    /// Im Wiedergabe-Mischer des Clients verworfene Frames je SSRC
+    #[serde(default)]
+    pub playback_overflow: Vec<SsrcOverflow>,
+}
+
+/// Beim Client angekommene, aber wegen voller Warteschlange verworfene Frames
+///
+/// Ein Sender, der schneller als in Echtzeit liefert, laeuft im Mischer des
+/// Empfaengers ueber. Das ist kein Netzwerkverlust.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SsrcOverflow {
+    pub ssrc: u32,
+    /// Verworfene Frames (kumuliert)
+    pub overflow: u64,
+}
+
+/// Zuordnung einer SSRC zu ihrem Benutzer
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SsrcSender {
+    pub ssrc: u32,
+    pub user_id: UserId,
+}
+
+/// Vom Server absichtlich nicht weitergeleitete Pakete einer SSRC
+///
+/// Stummschaltungen und verworfene veraltete Pakete sind kein Netzwerkverlust;
+/// der Empfaenger zieht sie von den erwarteten Paketen ab.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SsrcSuppressed {
+    pub ssrc: u32,
+    /// Verworfene Pakete (kumuliert)
+    pub suppressed: u64,
+}
+
+/// Antwort auf `VoiceStatsReport`: was der Server vom Client empfangen hat
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VoiceStatsResponse {
+    /// Empfangsstatistik des Servers fuer die SSRC des Clients (Uplink)
+    pub uplink: SsrcReceiveStats,
+    /// Benutzer zu den im Bericht genannten SSRCs
+    pub senders: Vec<SsrcSender>,
+    /// Serverseitig verworfene Pakete je SSRC (nur ohne Resequenzierung)
+    #[serde(default)]
+    pub suppressed: Vec<SsrcSuppressed>,
+}
+
+/// Anlass einer geaenderten Ziel-Bitrate
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VoiceQualityReason {
+    /// Paketverlust ueber dem Schwellwert
+    PacketLoss,
+    /// Hoher Verlust bei hoher RTT – starke Reduzierung
+    Critical,
+    /// Stabile Verbindung – Bitrate steigt wieder
+    Recovery,
+}
+
+/// Server -> Client: neue Ziel-Bitrate fuer den eigenen Voice-Stream
+///
+/// Der Server regelt die Bitrate je Client anhand der `VoiceStats`-Berichte
+/// und meldet nur Aenderungen, jeweils vor der `VoiceStatsResponse`. Der
+/// Client begrenzt den Wert auf die Grenzen seines Audio-Presets.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VoiceQualityUpdate {
+    pub target_bitrate_kbps: u16,
+    pub reason: VoiceQualityReason,
+}
+
+// ---------------------------------------------------------------------------
+// E2E-Schluesselverteilung
+// ---------------------------------------------------------------------------
+
+/// Oeffentlicher Schluessel eines Kanalmitglieds fuer die Schluesselverteilung
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct E2EMemberKey {
+    pub user_id: UserId,
+    /// X25519-Schluessel aus `VoiceInitRequest::e2e_public_key` (Base64)
+    pub public_key: String,
+}
+
+/// Server -> Schluesselverteiler: die Mitglieder des Kanals haben sich geaendert
+///
+/// Geht nur an den Verteiler des Kanals (das am laengsten anwesende Mitglied
+/// mit gemeldetem Schluessel). Er erzeugt einen neuen Gruppenschluessel fuer
+/// `epoch`, verschluesselt ihn fuer jedes Mitglied aus `members` (auch fuer
+/// sich selbst) und schickt ihn als `E2EKey` mit `GroupKeyDistribute`
+/// zurueck. Verteilungen fuer eine aeltere Epoch verwirft der Server.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct E2EKeyRotationRequiredEvent {
+    pub channel_id: ChannelId,
+    /// Epoch, die der neue Schluessel tragen muss
+    pub epoch: u32,
+    pub reason: crate::crypto::KeyRotationReason,
+    /// Empfaenger des neuen Schluessels
+    pub members: Vec<E2EMemberKey>,
+}
+
+/// E2E-Schluesselnachricht eines Kanals (Client <-> Server <-> Client)
+///
+/// Der Server liest nur Umschlag, Epoch und Empfaenger; die Schluessel selbst
+/// sind fuer die Empfaenger verschluesselt. Eine `GroupKeyDistribute` leitet
+/// er an jeden Empfaenger mit nur dessen eigenem Eintrag weiter. Erfolgreich
+/// angenommene Nachrichten werden nicht beantwortet.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct E2EKeyEnvelope {
+    pub channel_id: ChannelId,
+    pub message: crate::crypto::E2EKeyMessage,
+}
+
 // ---------------------------------------------------------------------------
 // Chat-Nachrichten
 // ---------------------------------------------------------------------------
 
+/// Hoechstlaenge einer Chat-Nachricht in Zeichen (Unicode-Skalarwerte)
+///
+/// Gilt fuer neue und bearbeitete Nachrichten. Der Server meldet seinen
+/// Wert in `Welcome`, damit Clients ihn vor dem Senden pruefen koennen.
+pub const MAX_NACHRICHT_ZEICHEN: usize = 4096;
+
 /// Chat-Nachricht senden
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ChatSendRequest {
@@ -490,6 +1642,10 @@ pub struct ChatHistoryRequest {
     pub before: Option<String>,
     /// Maximale Anzahl (Default: 50)
     pub limit: Option<i64>,
+    /// Verlauf in Teilstuecken (`ChatHistoryChunk` + `ChatHistoryComplete`)
+    /// statt als eine `ChatHistoryResponse` senden
+    #[serde(default)]
+    pub chunked: bool,
 }
 
 /// Einzelne Chat-Nachricht im Protokoll
@@ -522,6 +1678,110 @@ pub struct ChatHistoryResponse {
     pub messages: Vec<ChatMessageInfo>,
 }
 
+/// Teilstueck eines Chat-Verlaufs (`ChatHistoryRequest` mit `chunked`)
+///
+/// Traegt die `request_id` der Anfrage. Teilstuecke kommen in Reihenfolge,
+/// innerhalb und ueber Teilstuecke hinweg aelteste Nachricht zuerst.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChatHistoryChunk {
+    /// Kanal-ID
+    pub channel_id: ChannelId,
+    /// Laufende Nummer des Teilstuecks (ab 0)
+    pub index: u32,
+    /// Nachrichten dieses Teilstuecks (aelteste zuerst)
+    pub messages: Vec<ChatMessageInfo>,
+}
+
+/// Abschluss eines Chat-Verlaufs in Teilstuecken (die eigentliche Antwort)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChatHistoryComplete {
+    /// Kanal-ID
+    pub channel_id: ChannelId,
+    /// Anzahl gesendeter Teilstuecke
+    pub chunks: u32,
+    /// Anzahl Nachrichten ueber alle Teilstuecke
+    pub total: u32,
+    /// Cursor fuer die naechste Seite (`before`), None wenn nichts Aelteres existiert
+    pub next_before: Option<String>,
+}
+
+/// Nachrichten eines Kanals nach Text durchsuchen (nur fuer Kanalmitglieder)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChatSearchRequest {
+    /// Kanal-ID
+    pub channel_id: ChannelId,
+    /// Suchtext; `%`, `_` und `\` werden woertlich gesucht
+    pub query: String,
+    /// Maximale Anzahl (Default: 50, hoechstens 100)
+    pub limit: Option<i64>,
+    /// Nur Treffer vor diesem Zeitpunkt (Cursor, ISO8601)
+    pub before: Option<String>,
+    /// Nachrichten-ID zum Cursor (`next_before_id`); zeitgleiche Treffer
+    /// hinter dieser Nachricht werden noch geliefert
+    #[serde(default)]
+    pub before_id: Option<String>,
+}
+
+/// Treffer einer Chat-Suche
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChatSearchResponse {
+    /// Kanal-ID
+    pub channel_id: ChannelId,
+    /// Treffer (neueste zuerst, ohne geloeschte Nachrichten)
+    pub messages: Vec<ChatMessageInfo>,
+    /// Cursor fuer weitere Treffer (`before`), None wenn die Seite nicht voll war
+    pub next_before: Option<String>,
+    /// Nachrichten-ID zum Cursor (`before_id`)
+    #[serde(default)]
+    pub next_before_id: Option<String>,
+}
+
+/// Server -> Client: neue Chat-Nachricht in einem Kanal
+///
+/// Geht an alle Clients im Kanal ausser dem Absender, der die Nachricht
+/// bereits mit seiner `ChatSendResponse` kennt.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChatMessageEvent {
+    /// Die gespeicherte Nachricht
+    pub message: ChatMessageInfo,
+    /// Anzeigename des Absenders zum Sendezeitpunkt
+    pub sender_name: String,
+}
+
+/// Server -> Client: Chat-Nachricht wurde bearbeitet
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChatEditedEvent {
+    /// ID der Nachricht
+    pub message_id: String,
+    /// Kanal-ID
+    pub channel_id: ChannelId,
+    /// Neuer Inhalt
+    pub content: String,
+    /// Bearbeitungszeitpunkt (ISO8601)
+    pub edited_at: String,
+}
+
+/// Server -> Client: Chat-Nachricht wurde geloescht
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChatDeletedEvent {
+    /// ID der Nachricht
+    pub message_id: String,
+    /// Kanal-ID
+    pub channel_id: ChannelId,
+}
+
+/// Server -> Client: mehrere Nachrichten eines Kanals wurden geloescht
+///
+/// Nach einer Moderations-Bereinigung ein Event pro Kanal und Stapel statt
+/// eines `ChatDeleted` je Nachricht.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChatBulkDeletedEvent {
+    /// Kanal-ID
+    pub channel_id: ChannelId,
+    /// IDs der Nachrichten
+    pub message_ids: Vec<String>,
+}
+
 // ---------------------------------------------------------------------------
 // Keepalive
 // ---------------------------------------------------------------------------
@@ -551,27 +1811,59 @@ pub struct PongMessage {
 #[serde(tag = "type", rename_all = "snake_case")]
 pub enum ControlPayload {
     // Auth
+    Hello(HelloRequest),
+    Welcome(WelcomeResponse),
     Login(LoginRequest),
     LoginResponse(LoginResponse),
+    Register(RegisterRequest),
+    RegisterResponse(RegisterResponse),
     Logout(LogoutRequest),
     LogoutResponse(LogoutResponse),
+    SessionExpiryWarning(SessionExpiryWarningEvent),
+    SessionRenew,
+    SessionRenewResponse(SessionRenewResponse),
     PasswordChange(PasswordChangeRequest),
     PasswordChangeResponse(PasswordChangeResponse),
     NicknameChange(NicknameChangeRequest),
     NicknameChangeResponse(NicknameChangeResponse),
     SetAway(SetAwayRequest),
     SetAwayResponse(SetAwayResponse),
+    AccountDataExport,
+    AccountDataExportResponse(AccountDataExportResponse),
+    AccountExportReady(AccountExportReadyEvent),
+    AccountDelete(AccountDeleteRequest),
+    AccountDeleteResponse(AccountDeleteResponse),
 
     // Channel
-    ChannelList,
+    ChannelList(ChannelListRequest),
     ChannelListResponse(ChannelListResponse),
+    ChannelTreeExpand(ChannelTreeExpandRequest),
     ChannelJoin(ChannelJoinRequest),
     ChannelJoinResponse(ChannelJoinResponse),
+    ChannelMembers(ChannelMembersRequest),
+    ChannelMembersResponse(ChannelMembersResponse),
     ChannelLeave(ChannelLeaveRequest),
     ChannelCreate(ChannelCreateRequest),
     ChannelCreateResponse(ChannelCreateResponse),
     ChannelEdit(ChannelEditRequest),
+    ChannelEdited(ChannelEditedEvent),
     ChannelDelete(ChannelDeleteRequest),
+    ChannelTreeChanged(ChannelTreeChanged),
+    ChannelEmergencyMute(ChannelEmergencyMuteRequest),
+    ChannelEmergencyMuteEvent(ChannelEmergencyMuteEvent),
+    ChannelInvite(ChannelInviteRequest),
+    ChannelInviteResponse(ChannelInviteResponse),
+    ChannelInviteReceived(ChannelInviteEvent),
+    ChannelInviteAnswer(ChannelInviteAnswerRequest),
+    ChannelInviteResult(ChannelInviteResultEvent),
+    ChannelKnock(ChannelKnockRequest),
+    ChannelKnockResponse(ChannelKnockResponse),
+    ChannelKnockReceived(ChannelKnockEvent),
+    ChannelKnockAnswer(ChannelKnockAnswerRequest),
+    ChannelKnockResult(ChannelKnockResultEvent),
+    SoundboardPlay(SoundboardPlayRequest),
+    SoundboardStop(SoundboardStopRequest),
+    SoundboardPlayback(SoundboardPlaybackEvent),
 
     // Client
     ClientList,
@@ -579,26 +1871,58 @@ pub enum ControlPayload {
     ClientKick(ClientKickRequest),
     ClientBan(ClientBanRequest),
     ClientMove(ClientMoveRequest),
+    ClientMoved(ClientMovedEvent),
+    ClientsMoveAll(ClientsMoveAllRequest),
+    ClientsMoveAllResponse(ClientsMoveAllResponse),
+    ClientsMoved(ClientsMovedEvent),
+    ClientConnected(ClientConnectedEvent),
+    ClientDisconnected(ClientDisconnectedEvent),
+    ClientJoinedChannel(ClientJoinedChannelEvent),
+    ClientLeftChannel(ClientLeftChannelEvent),
+    ClientVoiceUpdated(ClientVoiceUpdatedEvent),
+    ClientSpeaking(ClientSpeakingEvent),
     ClientPoke(ClientPokeRequest),
     ClientUpdate(ClientUpdateRequest),
+    ClientUpdated(ClientUpdatedEvent),
+    ResolveIds(ResolveIdsRequest),
+    ResolveIdsResponse(ResolveIdsResponse),
+    // Client meldet echte Benutzereingaben (hoechstens einmal pro Minute)
+    ClientActivity,
+    StateDiff(StateDiffRequest),
+    StateDiffResponse(StateDiffResponse),
 
     // Server
     ServerInfo,
     ServerInfoResponse(ServerInfoResponse),
     ServerEdit(ServerEditRequest),
     ServerStop(ServerStopRequest),
+    PendingDisconnectNotice(PendingDisconnectNotice),
+    CancelPendingDisconnect(CancelPendingDisconnectRequest),
+    PendingDisconnectCancelled(PendingDisconnectCancelledEvent),
+    SessionCapture(SessionCaptureRequest),
+    SessionCaptureResponse(SessionCaptureResponse),
+    MotdChanged(MotdChangedEvent),
+    ServerAnnouncement(ServerAnnouncement),
 
     // Permission
-    PermissionList { target: String },
+    PermissionList {
+        target: String,
+    },
     PermissionListResponse(PermissionListResponse),
     PermissionAdd(PermissionAddRequest),
     PermissionRemove(PermissionRemoveRequest),
+    EffectivePermissions(EffectivePermissionsRequest),
+    EffectivePermissionsResponse(EffectivePermissionsResponse),
 
     // File
-    FileList { channel_id: ChannelId },
+    FileList {
+        channel_id: ChannelId,
+    },
     FileListResponse(FileListResponse),
     FileUpload(FileUploadRequest),
     FileUploadResponse(FileUploadResponse),
+    FileDownload(FileDownloadRequest),
+    FileDownloadResponse(FileDownloadResponse),
     FileDelete(FileDeleteRequest),
 
     // Chat
@@ -608,11 +1932,28 @@ pub enum ControlPayload {
     ChatDelete(ChatDeleteRequest),
     ChatHistory(ChatHistoryRequest),
     ChatHistoryResponse(ChatHistoryResponse),
+    ChatHistoryChunk(ChatHistoryChunk),
+    ChatHistoryComplete(ChatHistoryComplete),
+    ChatSearch(ChatSearchRequest),
+    ChatSearchResponse(ChatSearchResponse),
+    ChatMessage(ChatMessageEvent),
+    ChatEdited(ChatEditedEvent),
+    ChatDeleted(ChatDeletedEvent),
+    ChatBulkDeleted(ChatBulkDeletedEvent),
 
     // Voice Setup
     VoiceInit(VoiceInitRequest),
     VoiceReady(VoiceReadyResponse),
     VoiceDisconnect(VoiceDisconnectRequest),
+    VoiceStats(VoiceStatsReport),
+    VoiceStatsResponse(VoiceStatsResponse),
+    VoiceQualityUpdate(VoiceQualityUpdate),
+
+    // E2E-Schluesselverteilung
+    #[serde(rename = "e2e_key_rotation_required")]
+    E2EKeyRotationRequired(E2EKeyRotationRequiredEvent),
+    #[serde(rename = "e2e_key")]
+    E2EKey(E2EKeyEnvelope),
 
     // Keepalive
     Ping(PingMessage),
@@ -628,9 +1969,27 @@ pub struct ErrorResponse {
     pub code: ErrorCode,
     pub message: String,
     /// Optionale maschinenlesbare Details
+    ///
+    /// Aus einem [`SpeakeasyError`] erzeugte Antworten tragen hier den
+    /// stabilen Taxonomie-Code (`{"code": "channel.full", "nummer": 3002}`).
     pub details: Option<serde_json::Value>,
 }
 
+impl ErrorResponse {
+    /// Stabiler Taxonomie-Code der Antwort
+    ///
+    /// Faellt auf die grobe Zuordnung von `code` zurueck, wenn die Details
+    /// keinen (bekannten) Code enthalten, z.B. bei aelteren Servern.
+    pub fn fehler_code(&self) -> FehlerCode {
+        self.details
+            .as_ref()
+            .and_then(|d| d.get("code"))
+            .and_then(|c| c.as_str())
+            .and_then(FehlerCode::aus_name)
+            .unwrap_or_else(|| self.code.into())
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Control-Frame (Umschlag fuer alle Nachrichten)
 // ---------------------------------------------------------------------------
@@ -688,6 +2047,37 @@ impl ControlMessage {
         )
     }
 
+    /// Erstellt eine Fehler-Antwort aus der Fehler-Taxonomie
+    ///
+    /// Code, Meldung und Details stammen aus dem [`SpeakeasyError`].
+    pub fn fehler(request_id: u32, fehler: impl Into<SpeakeasyError>) -> Self {
+        let fehler = fehler.into();
+        let meldung = fehler.to_string();
+        Self::taxonomie_fehler(request_id, &fehler, meldung)
+    }
+
+    /// Wie [`ControlMessage::fehler`], mit vorangestelltem Kontext in der Meldung
+    pub fn fehler_mit_kontext(
+        request_id: u32,
+        kontext: &str,
+        fehler: impl Into<SpeakeasyError>,
+    ) -> Self {
+        let fehler = fehler.into();
+        let meldung = format!("{kontext}: {fehler}");
+        Self::taxonomie_fehler(request_id, &fehler, meldung)
+    }
+
+    fn taxonomie_fehler(request_id: u32, fehler: &SpeakeasyError, meldung: String) -> Self {
+        Self::new(
+            request_id,
+            ControlPayload::Error(ErrorResponse {
+                code: fehler.code().into(),
+                message: meldung,
+                details: Some(fehler.details()),
+            }),
+        )
+    }
+
     /// Serialisiert die Nachricht als JSON
     pub fn to_json(&self) -> serde_json::Result<String> {
         serde_json::to_string(self)
@@ -735,14 +2125,25 @@ pub enum CommandType {
 }
 
 /// Protokollversion (Phase 1)
-#[derive(Debug, Clone, Serialize, Deserialize)]
+///
+/// Gleiche Major-Version = kompatibel; Minor-Versionen fuegen nur hinzu.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 pub struct ProtokollVersion {
     pub major: u16,
     pub minor: u16,
 }
 
 impl ProtokollVersion {
-    pub const AKTUELL: Self = Self { major: 1, minor: 0 };
+    pub const AKTUELL: Self = Self {
+        major: 1,
+        minor: 45,
+    };
+}
+
+impl std::fmt::Display for ProtokollVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}.{}", self.major, self.minor)
+    }
 }
 
 // ---------------------------------------------------------------------------
@@ -780,6 +2181,51 @@ mod tests {
         }
     }
 
+    #[test]
+    fn taxonomie_fehler_traegt_stabilen_code() {
+        let msg = ControlMessage::fehler_mit_kontext(
+            7,
+            "Beitritt fehlgeschlagen",
+            SpeakeasyError::KanalVoll,
+        );
+        let decoded = ControlMessage::from_json(&msg.to_json().unwrap()).unwrap();
+        let ControlPayload::Error(e) = decoded.payload else {
+            panic!("Erwartet Error-Payload");
+        };
+        assert_eq!(e.code, ErrorCode::ChannelFull);
+        assert_eq!(e.message, "Beitritt fehlgeschlagen: Kanal ist voll");
+        assert_eq!(e.fehler_code(), FehlerCode::KanalVoll);
+        assert_eq!(e.details.unwrap()["nummer"], 3002);
+
+        // Ohne Details: grobe Zuordnung aus dem Protokoll-Code
+        let ControlPayload::Error(alt) = ControlMessage::error(1, ErrorCode::Banned, "weg").payload
+        else {
+            panic!("Erwartet Error-Payload");
+        };
+        assert_eq!(alt.fehler_code(), FehlerCode::Gebannt);
+    }
+
+    #[test]
+    fn jeder_taxonomie_code_hat_eine_protokoll_kategorie() {
+        for code in FehlerCode::ALLE {
+            let kategorie = ErrorCode::from(*code);
+            // Rueckweg landet im selben Bereich oder bei einer allgemeinen Kategorie
+            let zurueck = FehlerCode::from(kategorie);
+            assert!(
+                zurueck == *code
+                    || matches!(
+                        kategorie,
+                        ErrorCode::InternalError
+                            | ErrorCode::InvalidRequest
+                            | ErrorCode::NotFound
+                            | ErrorCode::PermissionDenied
+                            | ErrorCode::InvalidCredentials
+                    ),
+                "{code} -> {kategorie:?}"
+            );
+        }
+    }
+
     #[test]
     fn login_request_serialisierung() {
         let req = ControlMessage::new(
@@ -790,6 +2236,7 @@ mod tests {
                 token: None,
                 client_version: "1.0.0".to_string(),
                 display_name: Some("Test User".to_string()),
+                capture_consent: false,
             }),
         );
         let json = req.to_json().unwrap();
@@ -804,11 +2251,26 @@ mod tests {
 
     #[test]
     fn channel_list_request_serialisierung() {
-        let msg = ControlMessage::new(10, ControlPayload::ChannelList);
+        let msg = ControlMessage::new(10, ControlPayload::ChannelList(Default::default()));
         let json = msg.to_json().unwrap();
         let decoded = ControlMessage::from_json(&json).unwrap();
         assert_eq!(decoded.request_id, 10);
-        assert!(matches!(decoded.payload, ControlPayload::ChannelList));
+        assert!(matches!(decoded.payload, ControlPayload::ChannelList(_)));
+    }
+
+    #[test]
+    fn channel_list_ohne_parameter_bleibt_lesbar() {
+        // Aeltere Clients senden die Anfrage ohne Felder
+        let decoded =
+            ControlMessage::from_json(r#"{"request_id":3,"payload":{"type":"channel_list"}}"#)
+                .unwrap();
+        match decoded.payload {
+            ControlPayload::ChannelList(req) => {
+                assert!(req.parent_id.is_none());
+                assert!(req.depth.is_none());
+            }
+            _ => panic!("Erwartet ChannelList-Payload"),
+        }
     }
 
     #[test]
@@ -818,7 +2280,13 @@ mod tests {
             ControlPayload::VoiceInit(VoiceInitRequest {
                 client_udp_port: 4444,
                 preferred_codec: "opus".to_string(),
+                codec_negotiation: Some(CodecNegotiationRequest::fuer_preset(
+                    crate::codec::AudioPreset::Music,
+                )),
                 dtls_fingerprint: Some("AA:BB:CC".to_string()),
+                force_new: false,
+                resequencing: true,
+                e2e_public_key: None,
             }),
         );
         let json = req.to_json().unwrap();
@@ -826,11 +2294,72 @@ mod tests {
         if let ControlPayload::VoiceInit(v) = decoded.payload {
             assert_eq!(v.client_udp_port, 4444);
             assert_eq!(v.preferred_codec, "opus");
+            let anfrage = v.codec_negotiation.unwrap();
+            assert_eq!(anfrage.preset_hint, Some(crate::codec::AudioPreset::Music));
+            assert_eq!(anfrage.requested.bitrate_kbps, 192);
         } else {
             panic!("Erwartet VoiceInit-Payload");
         }
     }
 
+    #[test]
+    fn voice_init_ohne_force_new_setzt_sitzung_fort() {
+        let decoded = ControlMessage::from_json(
+            r#"{"request_id":4,"payload":{"type":"voice_init","client_udp_port":1,"preferred_codec":"opus","dtls_fingerprint":null}}"#,
+        )
+        .unwrap();
+        match decoded.payload {
+            ControlPayload::VoiceInit(req) => {
+                assert!(!req.force_new);
+                assert!(!req.resequencing);
+                assert!(req.codec_negotiation.is_none());
+            }
+            _ => panic!("Erwartet VoiceInit-Payload"),
+        }
+    }
+
+    #[test]
+    fn voice_ready_mit_vereinbarter_opus_konfiguration() {
+        use crate::codec::{AudioPreset, NegotiationStatus};
+
+        let antwort = CodecNegotiationResponse {
+            status: NegotiationStatus::Adjusted,
+            accepted: AudioPreset::Balanced.config(),
+            adjustment_reason: Some("Preset Musik nicht erlaubt".into()),
+            server_max_bitrate_kbps: 64,
+            preset: Some(AudioPreset::Balanced),
+        };
+        let msg = ControlMessage::new(
+            21,
+            ControlPayload::VoiceReady(VoiceReadyResponse {
+                server_udp_port: 9987,
+                server_ip: "127.0.0.1".into(),
+                ssrc: 7,
+                codec: "opus".into(),
+                codec_negotiation: Some(antwort.clone()),
+                server_dtls_fingerprint: None,
+                crypto_mode: "none".into(),
+                resequencing: true,
+                hello_nonce: None,
+            }),
+        );
+        let decoded = ControlMessage::from_json(&msg.to_json().unwrap()).unwrap();
+        let ControlPayload::VoiceReady(ready) = decoded.payload else {
+            panic!("Erwartet VoiceReady-Payload");
+        };
+        assert_eq!(ready.codec_negotiation, Some(antwort));
+
+        // Antworten aelterer Server enthalten keine Aushandlung
+        let alt = ControlMessage::from_json(
+            r#"{"request_id":5,"payload":{"type":"voice_ready","server_udp_port":1,"server_ip":"","ssrc":1,"codec":"opus","server_dtls_fingerprint":null,"crypto_mode":"none"}}"#,
+        )
+        .unwrap();
+        let ControlPayload::VoiceReady(ready) = alt.payload else {
+            panic!("Erwartet VoiceReady-Payload");
+        };
+        assert!(ready.codec_negotiation.is_none());
+    }
+
     #[test]
     fn permission_value_alle_varianten() {
         let grant = PermissionValue::Grant;
@@ -860,6 +2389,7 @@ mod tests {
                 reason: Some("Regelverstoß".to_string()),
                 duration_secs: Some(3600),
                 ban_ip: true,
+                remove_content_secs: Some(600),
             }),
         );
         let json = req.to_json().unwrap();
@@ -868,6 +2398,7 @@ mod tests {
             assert_eq!(b.target_user_id, uid);
             assert_eq!(b.duration_secs, Some(3600));
             assert!(b.ban_ip);
+            assert_eq!(b.remove_content_secs, Some(600));
         } else {
             panic!("Erwartet ClientBan-Payload");
         }
@@ -156,6 +156,13 @@ pub enum E2EKeyMessage {
     GroupKeyDistribute {
         /// Eindeutige Schluessel-ID (monoton steigend)
         key_id: u64,
+        /// Epoch des Schluessels (aus `E2EKeyRotationRequired`); steht auch
+        /// in Nonce und AAD jedes damit verschluesselten Voice-Pakets
+        #[serde(default)]
+        epoch: u32,
+        /// Algorithmus des Gruppenschluessels selbst
+        #[serde(default)]
+        key_algorithm: AeadAlgorithm,
         /// Zweck des Schluessels
         purpose: KeyPurpose,
         /// Map: user_id (String) -> verschluesselter Schluessel (Base64)
@@ -199,6 +206,8 @@ pub enum E2EKeyMessage {
 pub enum KeyRotationReason {
     /// Planmaessige Rotation (timer-basiert)
     Scheduled,
+    /// Mitglied ist dem Kanal beigetreten (oder hat seinen Schluessel gemeldet)
+    MemberJoined,
     /// Mitglied hat Kanal verlassen
     MemberLeft,
     /// Mitglied wurde gekickt/gebannt
@@ -291,6 +300,8 @@ mod tests {
 
         let msg = E2EKeyMessage::GroupKeyDistribute {
             key_id: 1,
+            epoch: 3,
+            key_algorithm: AeadAlgorithm::Aes256Gcm,
             purpose: KeyPurpose::Audio,
             encrypted_keys: keys,
             wrapping_algorithm: AeadAlgorithm::Aes256Gcm,
@@ -300,10 +311,14 @@ mod tests {
         let json = serde_json::to_string(&msg).unwrap();
         let decoded: E2EKeyMessage = serde_json::from_str(&json).unwrap();
         if let E2EKeyMessage::GroupKeyDistribute {
-            key_id, purpose, ..
+            key_id,
+            epoch,
+            purpose,
+            ..
         } = decoded
         {
             assert_eq!(key_id, 1);
+            assert_eq!(epoch, 3);
             assert_eq!(purpose, KeyPurpose::Audio);
         } else {
             panic!("Falscher Typ");
@@ -0,0 +1,341 @@
+//! Verbindungsaufbau: `Hello`/`Welcome` und Kompatibilitaetspruefung
+//!
+//! Der Client sendet vor dem Login `Hello` mit seiner Protokollversion und
+//! seinen Faehigkeiten, der Server antwortet mit `Welcome` ([`begruessen`]).
+//! Clients einer anderen Major-Version weist schon der Server ab; sonst
+//! nennt `Welcome` die Faehigkeiten, die beide Seiten beherrschen. Ob beide
+//! Seiten zusammenpassen, entscheidet der Client mit [`pruefen`]:
+//!
+//! - verschiedene Major-Versionen oder ein Client unter der vom Server
+//!   verlangten Mindestversion sind unvertraeglich ([`Inkompatibel`])
+//! - fehlen nur optionale Faehigkeiten, meldet sich der Client trotzdem an
+//!   und blendet die betroffenen Funktionen aus ([`Kompatibel::fehlend`])
+//!
+//! Server vor Protokoll [`HANDSHAKE_SEIT`] kennen `Hello` nicht und trennen
+//! die Verbindung beim Dekodieren; dafuer gibt es [`ohne_handshake`].
+
+use crate::control::{HelloRequest, ProtokollVersion, WelcomeResponse, MAX_NACHRICHT_ZEICHEN};
+
+/// Erste Protokollversion mit `Hello`/`Welcome`
+pub const HANDSHAKE_SEIT: ProtokollVersion = ProtokollVersion {
+    major: 1,
+    minor: 26,
+};
+
+/// Bekannte Faehigkeiten (Werte in `capabilities`)
+pub mod faehigkeit {
+    /// Chat in Kanaelen (Senden, Verlauf)
+    pub const CHAT: &str = "chat";
+    /// Chat-Ereignisse anderer Kanalmitglieder (`ChatMessage`, `ChatEdited`, ...)
+    pub const CHAT_EREIGNISSE: &str = "chat_events";
+    /// Server-Benachrichtigungen (`MotdChanged`, `ServerAnnouncement`)
+    pub const BENACHRICHTIGUNGEN: &str = "notifications";
+    /// Sprachuebertragung ueber UDP
+    pub const VOICE: &str = "voice";
+    /// Dateiablage in Kanaelen
+    pub const DATEIEN: &str = "files";
+}
+
+/// Alle Faehigkeiten dieses Protokollstands
+pub const ALLE_FAEHIGKEITEN: &[&str] = &[
+    faehigkeit::CHAT,
+    faehigkeit::CHAT_EREIGNISSE,
+    faehigkeit::BENACHRICHTIGUNGEN,
+    faehigkeit::VOICE,
+    faehigkeit::DATEIEN,
+];
+
+/// `Hello` mit der aktuellen Protokollversion und allen Faehigkeiten
+pub fn hello(client_name: impl Into<String>) -> HelloRequest {
+    HelloRequest {
+        protocol_version: ProtokollVersion::AKTUELL,
+        capabilities: ALLE_FAEHIGKEITEN.iter().map(|f| f.to_string()).collect(),
+        client_name: client_name.into(),
+    }
+}
+
+/// `Welcome` des Servers; bedient jeden Client derselben Major-Version
+pub fn welcome(server_version: impl Into<String>) -> WelcomeResponse {
+    WelcomeResponse {
+        protocol_version: ProtokollVersion::AKTUELL,
+        min_client_version: ProtokollVersion {
+            major: ProtokollVersion::AKTUELL.major,
+            minor: 0,
+        },
+        capabilities: ALLE_FAEHIGKEITEN.iter().map(|f| f.to_string()).collect(),
+        server_version: server_version.into(),
+        max_message_chars: MAX_NACHRICHT_ZEICHEN as u32,
+    }
+}
+
+/// Serverseitige Antwort auf `Hello`
+///
+/// Weist Clients mit anderer Major-Version ab. Sonst enthaelt `Welcome`
+/// nur die Faehigkeiten, die auch der Client angeboten hat.
+pub fn begruessen(
+    hello: &HelloRequest,
+    server_version: impl Into<String>,
+) -> Result<WelcomeResponse, Inkompatibel> {
+    let client = hello.protocol_version;
+    let server = ProtokollVersion::AKTUELL;
+    if client.major != server.major {
+        let hinweis = if client.major < server.major {
+            "Bitte den Client aktualisieren."
+        } else {
+            "Bitte den Server-Betreiber um ein Update oder einen aelteren Client verwenden."
+        };
+        return Err(Inkompatibel {
+            client,
+            server: Some(server),
+            hinweis: format!(
+                "Der Server bedient nur Clients mit Protokoll {}.x. {hinweis}",
+                server.major
+            ),
+        });
+    }
+
+    let mut welcome = welcome(server_version);
+    welcome
+        .capabilities
+        .retain(|f| hello.capabilities.contains(f));
+    Ok(welcome)
+}
+
+/// Client und Server passen zusammen
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Kompatibel {
+    /// Faehigkeiten, die beide Seiten unterstuetzen
+    pub gemeinsam: Vec<String>,
+    /// Benoetigte Faehigkeiten, die der Server nicht anbietet
+    pub fehlend: Vec<String>,
+}
+
+/// Client und Server passen nicht zusammen
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Inkompatibel {
+    /// Protokollversion des Clients
+    pub client: ProtokollVersion,
+    /// Protokollversion des Servers (`None` = Server vor dem Handshake)
+    pub server: Option<ProtokollVersion>,
+    /// Verstaendlicher Hinweis, was zu tun ist
+    pub hinweis: String,
+}
+
+impl std::fmt::Display for Inkompatibel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.server {
+            Some(server) => write!(
+                f,
+                "Protokoll nicht kompatibel (Client {}, Server {}): {}",
+                self.client, server, self.hinweis
+            ),
+            None => write!(
+                f,
+                "Protokoll nicht kompatibel (Client {}): {}",
+                self.client, self.hinweis
+            ),
+        }
+    }
+}
+
+/// Prueft die `Welcome`-Antwort gegen die eigene `Hello`-Nachricht
+///
+/// `benoetigt` sind die Faehigkeiten, auf die die Oberflaeche baut; fehlen
+/// sie beim Server, landen sie in [`Kompatibel::fehlend`].
+pub fn pruefen(
+    hello: &HelloRequest,
+    welcome: &WelcomeResponse,
+    benoetigt: &[&str],
+) -> Result<Kompatibel, Inkompatibel> {
+    let client = hello.protocol_version;
+    let server = welcome.protocol_version;
+
+    if server.major < client.major {
+        return Err(Inkompatibel {
+            client,
+            server: Some(server),
+            hinweis: format!(
+                "Der Server ist veraltet; dieser Client erfordert einen Server mit \
+                 Protokoll >= {}.0. Bitte den Server-Betreiber um ein Update oder \
+                 einen aelteren Client verwenden.",
+                client.major
+            ),
+        });
+    }
+    let mindestens = welcome.min_client_version.max(ProtokollVersion {
+        major: server.major,
+        minor: 0,
+    });
+    if client < mindestens {
+        return Err(Inkompatibel {
+            client,
+            server: Some(server),
+            hinweis: format!(
+                "Der Server erfordert einen Client mit Protokoll >= {mindestens}. \
+                 Bitte den Client aktualisieren."
+            ),
+        });
+    }
+
+    let gemeinsam = hello
+        .capabilities
+        .iter()
+        .filter(|f| welcome.capabilities.contains(f))
+        .cloned()
+        .collect::<Vec<_>>();
+    let fehlend = benoetigt
+        .iter()
+        .filter(|f| !gemeinsam.iter().any(|g| g == *f))
+        .map(|f| f.to_string())
+        .collect();
+    Ok(Kompatibel { gemeinsam, fehlend })
+}
+
+/// Der Server hat die Verbindung auf `Hello` hin getrennt
+///
+/// So reagieren Server vor [`HANDSHAKE_SEIT`] auf die unbekannte Nachricht.
+pub fn ohne_handshake(client: ProtokollVersion) -> Inkompatibel {
+    Inkompatibel {
+        client,
+        server: None,
+        hinweis: format!(
+            "Der Server ist aelter als Protokoll {HANDSHAKE_SEIT} und kennt den \
+             Verbindungsaufbau dieses Clients nicht. Bitte den Server-Betreiber um \
+             ein Update oder einen aelteren Client verwenden."
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn version(major: u16, minor: u16) -> ProtokollVersion {
+        ProtokollVersion { major, minor }
+    }
+
+    #[test]
+    fn server_weist_andere_major_version_ab() {
+        for major in [
+            ProtokollVersion::AKTUELL.major - 1,
+            ProtokollVersion::AKTUELL.major + 1,
+        ] {
+            let mut client = hello("test");
+            client.protocol_version = version(major, 0);
+            let fehler = begruessen(&client, "0.1.0").unwrap_err();
+            assert_eq!(fehler.client, version(major, 0));
+            assert_eq!(fehler.server, Some(ProtokollVersion::AKTUELL));
+        }
+
+        let mut client = hello("test");
+        client.protocol_version = version(ProtokollVersion::AKTUELL.major, 0);
+        assert!(begruessen(&client, "0.1.0").is_ok());
+    }
+
+    #[test]
+    fn welcome_nennt_nur_gemeinsame_faehigkeiten() {
+        let mut client = hello("test");
+        client.capabilities = vec![
+            faehigkeit::CHAT.to_string(),
+            faehigkeit::VOICE.to_string(),
+            "zukunft".to_string(),
+        ];
+        let welcome = begruessen(&client, "0.1.0").unwrap();
+        assert_eq!(
+            welcome.capabilities,
+            vec![faehigkeit::CHAT, faehigkeit::VOICE]
+        );
+        assert_eq!(welcome.server_version, "0.1.0");
+
+        let kompatibel = pruefen(&client, &welcome, &[faehigkeit::DATEIEN]).unwrap();
+        assert_eq!(
+            kompatibel.gemeinsam,
+            vec![faehigkeit::CHAT, faehigkeit::VOICE]
+        );
+        assert_eq!(kompatibel.fehlend, vec![faehigkeit::DATEIEN]);
+    }
+
+    #[test]
+    fn gleiche_major_version_ist_kompatibel() {
+        let mut server = welcome("0.1.0");
+        server.protocol_version = version(ProtokollVersion::AKTUELL.major, 99);
+        let ergebnis = pruefen(&hello("test"), &server, &[faehigkeit::CHAT]).unwrap();
+        assert_eq!(ergebnis.gemeinsam.len(), ALLE_FAEHIGKEITEN.len());
+        assert!(ergebnis.fehlend.is_empty());
+    }
+
+    #[test]
+    fn fehlende_faehigkeiten_werden_gemeldet() {
+        let mut server = welcome("0.1.0");
+        server
+            .capabilities
+            .retain(|f| f != faehigkeit::CHAT_EREIGNISSE && f != faehigkeit::BENACHRICHTIGUNGEN);
+        let ergebnis = pruefen(
+            &hello("test"),
+            &server,
+            &[
+                faehigkeit::CHAT,
+                faehigkeit::CHAT_EREIGNISSE,
+                faehigkeit::BENACHRICHTIGUNGEN,
+            ],
+        )
+        .unwrap();
+        assert_eq!(
+            ergebnis.fehlend,
+            vec![faehigkeit::CHAT_EREIGNISSE, faehigkeit::BENACHRICHTIGUNGEN]
+        );
+        assert!(!ergebnis
+            .gemeinsam
+            .iter()
+            .any(|f| f == faehigkeit::CHAT_EREIGNISSE));
+    }
+
+    #[test]
+    fn neuere_server_major_verlangt_client_update() {
+        let mut server = welcome("2.0.0");
+        server.protocol_version = version(2, 0);
+        server.min_client_version = version(2, 0);
+        let mut client = hello("test");
+        client.protocol_version = version(1, 26);
+
+        let fehler = pruefen(&client, &server, &[]).unwrap_err();
+        assert_eq!(fehler.server, Some(version(2, 0)));
+        assert!(fehler.hinweis.contains("Client mit Protokoll >= 2.0"));
+    }
+
+    #[test]
+    fn mindestversion_des_servers_gilt_auch_innerhalb_der_major_version() {
+        let mut server = welcome("1.9.0");
+        server.protocol_version = version(1, 40);
+        server.min_client_version = version(1, 30);
+        let mut client = hello("test");
+        client.protocol_version = version(1, 29);
+
+        let fehler = pruefen(&client, &server, &[]).unwrap_err();
+        assert!(fehler.hinweis.contains(">= 1.30"));
+
+        client.protocol_version = version(1, 30);
+        assert!(pruefen(&client, &server, &[]).is_ok());
+    }
+
+    #[test]
+    fn aelterer_server_major_ist_inkompatibel() {
+        let mut server = welcome("0.9.0");
+        server.protocol_version = version(0, 12);
+        server.min_client_version = version(0, 0);
+        let mut client = hello("test");
+        client.protocol_version = version(1, 26);
+
+        let fehler = pruefen(&client, &server, &[]).unwrap_err();
+        assert!(fehler.hinweis.contains("Server mit Protokoll >= 1.0"));
+        assert!(fehler.to_string().contains("Server 0.12"));
+    }
+
+    #[test]
+    fn welcome_ohne_nachrichtenlimit_nutzt_standard() {
+        let mut json = serde_json::to_value(welcome("1.0.0")).unwrap();
+        json.as_object_mut().unwrap().remove("max_message_chars");
+        let alt: WelcomeResponse = serde_json::from_value(json).unwrap();
+        assert_eq!(alt.max_message_chars as usize, MAX_NACHRICHT_ZEICHEN);
+    }
+}
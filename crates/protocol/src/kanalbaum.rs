@@ -0,0 +1,456 @@
+//! Kanalbaum-Cache auf Client-Seite
+//!
+//! Grosse Server liefern den Kanalbaum nur teilweise aus (Root-Ebene plus
+//! Pfad zum eigenen Kanal, siehe `ChannelListRequest`). Der Cache fuehrt
+//! Teilantworten zusammen:
+//!
+//! - [`KanalbaumCache::liste_uebernehmen`] fuer Antworten auf `ChannelList`
+//!   ohne `parent_id` – die Root-Ebene ist darin immer vollstaendig
+//! - [`KanalbaumCache::teilbaum_uebernehmen`] fuer `ChannelTreeExpand` und
+//!   `ChannelList` mit `parent_id`
+//!
+//! Kanal-Ereignisse werden an alle Clients verteilt, nicht nur an die
+//! Mitglieder eines Kanals. [`KanalbaumCache::anwenden`] haelt deshalb auch
+//! geladene Zweige aktuell, in denen der Client selbst nicht ist.
+
+use std::collections::{HashMap, HashSet};
+
+use speakeasy_core::types::ChannelId;
+
+use crate::control::{ChannelInfo, ChannelListResponse, ControlPayload};
+
+/// Geladene Kanaele eines Servers
+#[derive(Debug, Clone, Default)]
+pub struct KanalbaumCache {
+    kanaele: HashMap<ChannelId, ChannelInfo>,
+    /// `true` solange der ganze Baum geladen ist
+    vollstaendig: bool,
+}
+
+impl KanalbaumCache {
+    pub fn neu() -> Self {
+        Self::default()
+    }
+
+    /// Gibt `true` zurueck wenn der Server den ganzen Baum geliefert hat
+    pub fn ist_vollstaendig(&self) -> bool {
+        self.vollstaendig
+    }
+
+    /// Anzahl geladener Kanaele
+    pub fn len(&self) -> usize {
+        self.kanaele.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.kanaele.is_empty()
+    }
+
+    pub fn kanal(&self, channel_id: &ChannelId) -> Option<&ChannelInfo> {
+        self.kanaele.get(channel_id)
+    }
+
+    pub fn ist_geladen(&self, channel_id: &ChannelId) -> bool {
+        self.kanaele.contains_key(channel_id)
+    }
+
+    /// Geladene Unterkanaele (None = Root-Ebene), sortiert
+    pub fn kinder(&self, parent_id: Option<ChannelId>) -> Vec<&ChannelInfo> {
+        let mut kinder: Vec<&ChannelInfo> = self
+            .kanaele
+            .values()
+            .filter(|k| self.eltern(k) == parent_id)
+            .collect();
+        kinder.sort_by(|a, b| {
+            (a.sort_order, &a.name, a.channel_id.inner()).cmp(&(
+                b.sort_order,
+                &b.name,
+                b.channel_id.inner(),
+            ))
+        });
+        kinder
+    }
+
+    /// Alle geladenen Kanaele in Baumreihenfolge (Eltern vor Kindern)
+    pub fn in_baumreihenfolge(&self) -> Vec<&ChannelInfo> {
+        let mut ergebnis = Vec::with_capacity(self.kanaele.len());
+        let mut stapel: Vec<&ChannelInfo> = self.kinder(None).into_iter().rev().collect();
+        while let Some(kanal) = stapel.pop() {
+            ergebnis.push(kanal);
+            stapel.extend(self.kinder(Some(kanal.channel_id)).into_iter().rev());
+        }
+        ergebnis
+    }
+
+    /// Kanaele mit noch nicht geladenen Unterkanaelen
+    pub fn nachzuladen(&self) -> Vec<ChannelId> {
+        self.kanaele
+            .values()
+            .filter(|k| k.has_children)
+            .map(|k| k.channel_id)
+            .collect()
+    }
+
+    /// Verwirft den Cache (Verbindung getrennt)
+    pub fn leeren(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Uebernimmt die Antwort auf `ChannelList` ohne `parent_id`
+    ///
+    /// Eine vollstaendige Antwort ersetzt den Cache. Bei einer Teilantwort
+    /// bleiben bereits aufgeklappte Zweige erhalten; Root-Kanaele, die nicht
+    /// mehr geliefert werden, fallen samt Unterbaum heraus.
+    pub fn liste_uebernehmen(&mut self, antwort: &ChannelListResponse) {
+        if !antwort.partial {
+            self.kanaele = antwort
+                .channels
+                .iter()
+                .map(|k| (k.channel_id, k.clone()))
+                .collect();
+            self.vollstaendig = true;
+            return;
+        }
+
+        let wurzeln: HashSet<ChannelId> = antwort
+            .channels
+            .iter()
+            .filter(|k| k.parent_id.is_none())
+            .map(|k| k.channel_id)
+            .collect();
+        let veraltet: Vec<ChannelId> = self
+            .kinder(None)
+            .into_iter()
+            .map(|k| k.channel_id)
+            .filter(|id| !wurzeln.contains(id))
+            .collect();
+        for channel_id in veraltet {
+            self.entfernen(channel_id);
+        }
+        self.teilbaum_uebernehmen(antwort);
+    }
+
+    /// Fuehrt einen nachgeladenen Teilbaum in den Cache ein
+    ///
+    /// Fuer Kanaele, deren Unterkanaele vollstaendig mitgeliefert wurden
+    /// (`has_children == false`), werden nicht mehr vorhandene Kinder entfernt.
+    pub fn teilbaum_uebernehmen(&mut self, antwort: &ChannelListResponse) {
+        if antwort.partial {
+            self.vollstaendig = false;
+        }
+
+        let geliefert: HashSet<ChannelId> = antwort.channels.iter().map(|k| k.channel_id).collect();
+        for kanal in &antwort.channels {
+            self.einfuegen(kanal.clone());
+        }
+
+        for kanal in antwort.channels.iter().filter(|k| !k.has_children) {
+            let veraltet: Vec<ChannelId> = self
+                .kinder(Some(kanal.channel_id))
+                .into_iter()
+                .map(|k| k.channel_id)
+                .filter(|id| !geliefert.contains(id))
+                .collect();
+            for channel_id in veraltet {
+                self.entfernen(channel_id);
+            }
+        }
+    }
+
+    /// Uebernimmt ein Kanal-Ereignis des Servers
+    ///
+    /// Gibt `true` zurueck wenn sich der Cache geaendert hat. Ereignisse fuer
+    /// nicht geladene Zweige werden ignoriert – sie kommen beim Aufklappen
+    /// ohnehin aktuell vom Server.
+    pub fn anwenden(&mut self, payload: &ControlPayload) -> bool {
+        match payload {
+            ControlPayload::ClientMoved(ereignis) => {
+                let mut geaendert = false;
+                if let Some(von) = ereignis
+                    .from_channel_id
+                    .and_then(|id| self.kanaele.get_mut(&id))
+                {
+                    von.current_clients = von.current_clients.saturating_sub(1);
+                    geaendert = true;
+                }
+                if let Some(nach) = self.kanaele.get_mut(&ereignis.to_channel_id) {
+                    nach.current_clients += 1;
+                    geaendert = true;
+                }
+                geaendert
+            }
+            ControlPayload::ClientsMoved(ereignis) => {
+                let anzahl = ereignis.user_ids.len() as u32;
+                let mut geaendert = false;
+                if let Some(von) = self.kanaele.get_mut(&ereignis.from_channel_id) {
+                    von.current_clients = von.current_clients.saturating_sub(anzahl);
+                    geaendert = true;
+                }
+                if let Some(nach) = self.kanaele.get_mut(&ereignis.to_channel_id) {
+                    nach.current_clients += anzahl;
+                    geaendert = true;
+                }
+                geaendert
+            }
+            ControlPayload::ClientJoinedChannel(ereignis) => {
+                match self.kanaele.get_mut(&ereignis.channel_id) {
+                    Some(kanal) => {
+                        kanal.current_clients += 1;
+                        true
+                    }
+                    None => false,
+                }
+            }
+            ControlPayload::ClientLeftChannel(ereignis) => {
+                match self.kanaele.get_mut(&ereignis.channel_id) {
+                    Some(kanal) => {
+                        kanal.current_clients = kanal.current_clients.saturating_sub(1);
+                        true
+                    }
+                    None => false,
+                }
+            }
+            ControlPayload::ChannelTreeChanged(ereignis) => {
+                if self.ist_geladen(&ereignis.root_id) {
+                    return false;
+                }
+                match ereignis.parent_id {
+                    // Neuer Zweig unter einem geladenen Kanal: muss nachgeladen werden
+                    Some(parent_id) => match self.kanaele.get_mut(&parent_id) {
+                        Some(parent) => {
+                            parent.child_count += 1;
+                            parent.has_children = true;
+                            self.vollstaendig = false;
+                            true
+                        }
+                        None => false,
+                    },
+                    // Neue Root-Kanaele kommen mit der naechsten Kanalliste
+                    None => {
+                        self.vollstaendig = false;
+                        true
+                    }
+                }
+            }
+            ControlPayload::ChannelEdited(ereignis) => {
+                let Some(alt) = self.kanaele.get_mut(&ereignis.channel.channel_id) else {
+                    return false;
+                };
+                // Unterkanaele kennt das Ereignis nicht, die bleiben wie geladen
+                *alt = ChannelInfo {
+                    has_children: alt.has_children,
+                    child_count: alt.child_count,
+                    ..ereignis.channel.clone()
+                };
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn einfuegen(&mut self, mut kanal: ChannelInfo) {
+        // Bereits aufgeklappt und unveraendert: nicht erneut als offen markieren
+        if let Some(alt) = self.kanaele.get(&kanal.channel_id) {
+            if kanal.has_children && !alt.has_children && alt.child_count == kanal.child_count {
+                kanal.has_children = false;
+            }
+        }
+        self.kanaele.insert(kanal.channel_id, kanal);
+    }
+
+    /// Entfernt einen Kanal samt geladenem Unterbaum
+    fn entfernen(&mut self, channel_id: ChannelId) {
+        let mut stapel = vec![channel_id];
+        while let Some(id) = stapel.pop() {
+            self.kanaele.remove(&id);
+            stapel.extend(
+                self.kanaele
+                    .values()
+                    .filter(|k| k.parent_id == Some(id))
+                    .map(|k| k.channel_id),
+            );
+        }
+    }
+
+    /// Eltern im Cache; Kanaele mit nicht geladenem Eltern gelten als Root
+    fn eltern(&self, kanal: &ChannelInfo) -> Option<ChannelId> {
+        kanal.parent_id.filter(|p| self.kanaele.contains_key(p))
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::control::{
+        ChannelEditedEvent, ChannelTreeChanged, ClientMovedEvent, ClientsMovedEvent,
+    };
+    use uuid::Uuid;
+
+    fn id(n: u128) -> ChannelId {
+        ChannelId(Uuid::from_u128(n))
+    }
+
+    fn kanal(n: u128, parent: Option<u128>, child_count: u32, offen: bool) -> ChannelInfo {
+        ChannelInfo {
+            channel_id: id(n),
+            name: format!("Kanal {n}"),
+            description: None,
+            parent_id: parent.map(id),
+            sort_order: n as i32,
+            max_clients: None,
+            current_clients: 0,
+            password_protected: false,
+            codec: "opus".into(),
+            codec_quality: 7,
+            has_children: offen,
+            child_count,
+            slow_mode_secs: 0,
+            edit_window_secs: 0,
+            join_by_approval: false,
+        }
+    }
+
+    fn antwort(channels: Vec<ChannelInfo>, partial: bool) -> ChannelListResponse {
+        ChannelListResponse { channels, partial }
+    }
+
+    fn ids(kanaele: Vec<&ChannelInfo>) -> Vec<ChannelId> {
+        kanaele.into_iter().map(|k| k.channel_id).collect()
+    }
+
+    #[test]
+    fn teilantworten_werden_zusammengefuehrt() {
+        let mut cache = KanalbaumCache::neu();
+        // Root-Ebene: 1 (mit 2 Kindern, nicht geladen), 2 (leer)
+        cache.liste_uebernehmen(&antwort(
+            vec![kanal(1, None, 2, true), kanal(2, None, 0, false)],
+            true,
+        ));
+        assert!(!cache.ist_vollstaendig());
+        assert_eq!(cache.nachzuladen(), vec![id(1)]);
+
+        cache.teilbaum_uebernehmen(&antwort(
+            vec![
+                kanal(1, None, 2, false),
+                kanal(10, Some(1), 0, false),
+                kanal(11, Some(1), 3, true),
+            ],
+            true,
+        ));
+        assert_eq!(cache.len(), 4);
+        assert_eq!(cache.nachzuladen(), vec![id(11)]);
+        assert_eq!(
+            ids(cache.in_baumreihenfolge()),
+            vec![id(1), id(10), id(11), id(2)]
+        );
+
+        // Erneute Root-Liste behaelt den aufgeklappten Zweig
+        cache.liste_uebernehmen(&antwort(
+            vec![kanal(1, None, 2, true), kanal(2, None, 0, false)],
+            true,
+        ));
+        assert_eq!(cache.len(), 4);
+        assert!(!cache.kanal(&id(1)).unwrap().has_children);
+    }
+
+    #[test]
+    fn geloeschte_kanaele_fallen_heraus() {
+        let mut cache = KanalbaumCache::neu();
+        cache.liste_uebernehmen(&antwort(
+            vec![kanal(1, None, 1, true), kanal(2, None, 0, false)],
+            true,
+        ));
+        cache.teilbaum_uebernehmen(&antwort(
+            vec![kanal(1, None, 1, false), kanal(10, Some(1), 0, false)],
+            true,
+        ));
+
+        // Kind 10 geloescht
+        cache.teilbaum_uebernehmen(&antwort(vec![kanal(1, None, 0, false)], true));
+        assert!(!cache.ist_geladen(&id(10)));
+
+        // Root-Kanal 2 geloescht
+        cache.liste_uebernehmen(&antwort(vec![kanal(1, None, 0, false)], true));
+        assert!(!cache.ist_geladen(&id(2)));
+
+        // Vollstaendige Antwort ersetzt alles
+        cache.liste_uebernehmen(&antwort(vec![kanal(3, None, 0, false)], false));
+        assert!(cache.ist_vollstaendig());
+        assert_eq!(ids(cache.in_baumreihenfolge()), vec![id(3)]);
+    }
+
+    #[test]
+    fn ereignisse_fuer_geladene_zweige() {
+        let mut cache = KanalbaumCache::neu();
+        cache.liste_uebernehmen(&antwort(
+            vec![kanal(1, None, 1, false), kanal(10, Some(1), 0, false)],
+            false,
+        ));
+
+        // Client wechselt von einem nicht geladenen Kanal nach 10
+        assert!(
+            cache.anwenden(&ControlPayload::ClientMoved(ClientMovedEvent {
+                user_id: speakeasy_core::types::UserId(Uuid::from_u128(7)),
+                from_channel_id: Some(id(99)),
+                to_channel_id: id(10),
+                reason: None,
+                state_version: 0,
+            }))
+        );
+        assert_eq!(cache.kanal(&id(10)).unwrap().current_clients, 1);
+
+        // Sammel-Move von 10 nach 1
+        assert!(
+            cache.anwenden(&ControlPayload::ClientsMoved(ClientsMovedEvent {
+                user_ids: vec![speakeasy_core::types::UserId(Uuid::from_u128(7))],
+                from_channel_id: id(10),
+                to_channel_id: id(1),
+                reason: None,
+                state_version: 0,
+            }))
+        );
+        assert_eq!(cache.kanal(&id(10)).unwrap().current_clients, 0);
+        assert_eq!(cache.kanal(&id(1)).unwrap().current_clients, 1);
+
+        // Neuer Zweig unter 10 muss nachgeladen werden
+        assert!(
+            cache.anwenden(&ControlPayload::ChannelTreeChanged(ChannelTreeChanged {
+                root_id: id(20),
+                parent_id: Some(id(10)),
+                created: vec![id(20)],
+            }))
+        );
+        assert_eq!(cache.nachzuladen(), vec![id(10)]);
+        assert!(!cache.ist_vollstaendig());
+
+        // Zweig unter einem nicht geladenen Kanal wird ignoriert
+        assert!(
+            !cache.anwenden(&ControlPayload::ChannelTreeChanged(ChannelTreeChanged {
+                root_id: id(30),
+                parent_id: Some(id(99)),
+                created: vec![id(30)],
+            }))
+        );
+
+        // Bearbeiteter Kanal: neue Einstellungen, Unterkanaele bleiben
+        let mut bearbeitet = kanal(10, Some(1), 0, false);
+        bearbeitet.slow_mode_secs = 30;
+        assert!(
+            cache.anwenden(&ControlPayload::ChannelEdited(ChannelEditedEvent {
+                channel: bearbeitet,
+            }))
+        );
+        let k10 = cache.kanal(&id(10)).unwrap();
+        assert_eq!(k10.slow_mode_secs, 30);
+        assert_eq!(k10.child_count, 1);
+        assert!(
+            !cache.anwenden(&ControlPayload::ChannelEdited(ChannelEditedEvent {
+                channel: kanal(99, None, 0, false),
+            }))
+        );
+    }
+}
@@ -9,10 +9,32 @@
 //! - `crypto`  – DTLS/E2E Krypto-Typen (Implementierung in Phase 5)
 //! - `codec`   – Opus-Konfiguration und Audio-Presets
 //! - `wire`    – TCP Frame-Codec (tokio-util Encoder/Decoder)
+//! - `qos`     – DSCP-Markierung fuer Voice- und Control-Sockets
+//! - `socket_statistik` – Socket-Puffer und Kernel-Drop-Zaehler fuer UDP
+//! - `ssrc`    – SSRC->Benutzer-Zuordnung fuer Clients
+//! - `udp_fehler` – Einordnung von UDP-Socket-Fehlern (ICMP-Rueckmeldungen)
+//! - `sprecher` – Sprechanzeige fuer Clients (Mitgliederliste)
+//! - `kanalbaum` – Kanalbaum-Cache fuer Clients (Teilbaeume zusammenfuehren)
+//! - `presenz` – Presence-Abbild fuer Clients (Kanal je Benutzer, StateDiff)
+//! - `namen` – Namens-Cache fuer Clients (Benutzer- und Kanalnamen, ResolveIds)
+//! - `chat_verlauf` – Chat-Verlauf in Teilstuecken unterhalb der Frame-Groesse
+//! - `handshake` – Hello/Welcome und Kompatibilitaetspruefung vor dem Login
+//! - `conformance` – Kanonische Testvektoren fuer alternative Implementierungen
 
+pub mod chat_verlauf;
 pub mod codec;
+pub mod conformance;
 pub mod control;
 pub mod crypto;
+pub mod handshake;
+pub mod kanalbaum;
+pub mod namen;
+pub mod presenz;
+pub mod qos;
+pub mod socket_statistik;
+pub mod sprecher;
+pub mod ssrc;
+pub mod udp_fehler;
 pub mod voice;
 pub mod wire;
 
@@ -20,5 +42,8 @@ pub mod wire;
 pub use codec::{AudioPreset, CodecNegotiationRequest, CodecNegotiationResponse, OpusConfig};
 pub use control::{ControlMessage, ControlPayload, ErrorCode, ErrorResponse};
 pub use crypto::{CryptoMode, E2EKeyMessage, KeyExchangeMessage};
-pub use voice::{PacketType, VoiceFlags, VoicePacket, VoicePacketHeader, VoicePaket};
+pub use voice::{
+    PacketType, PingPaket, SequenzStatistik, VoiceFlags, VoicePacket, VoicePacketHeader,
+    VoicePaket, PING_GROESSE,
+};
 pub use wire::{FrameCodec, DEFAULT_MAX_FRAME_SIZE};
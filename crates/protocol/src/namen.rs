@@ -0,0 +1,312 @@
+//! Namens-Cache auf Client-Seite
+//!
+//! Chat-Verlauf und Ereignisse verweisen auf Benutzer und Kanaele nur per
+//! ID. Der Cache haelt die zugehoerigen Namen fuer die Anzeige:
+//!
+//! - [`NamensCache::anwenden`] uebernimmt Namen aus Snapshots (`ClientList`,
+//!   `ChannelList`) und haelt sie mit `ClientUpdated`/`ChannelEdited` aktuell
+//! - [`NamensCache::fehlende`] bildet `ResolveIds`-Anfragen fuer unbekannte
+//!   IDs (z.B. Absender alter Nachrichten, die nicht mehr verbunden sind)
+//! - [`NamensCache::aufloesung_uebernehmen`] uebernimmt die Antwort darauf,
+//!   inklusive Platzhalternamen fuer geloeschte Konten
+//!
+//! Getrennte Clients bleiben im Cache: ihre Namen werden fuer den
+//! Chat-Verlauf weiter gebraucht.
+
+use std::collections::{HashMap, HashSet};
+
+use speakeasy_core::types::{ChannelId, UserId};
+
+use crate::control::{
+    ChannelInfo, ClientInfo, ControlPayload, ResolveIdsRequest, ResolveIdsResponse,
+    ResolvedChannel, ResolvedUser, MAX_AUFLOESUNG_IDS,
+};
+
+/// Namen bekannter Benutzer und Kanaele eines Servers
+#[derive(Debug, Clone, Default)]
+pub struct NamensCache {
+    benutzer: HashMap<UserId, ResolvedUser>,
+    kanaele: HashMap<ChannelId, ResolvedChannel>,
+}
+
+impl NamensCache {
+    pub fn neu() -> Self {
+        Self::default()
+    }
+
+    pub fn benutzer(&self, user_id: &UserId) -> Option<&ResolvedUser> {
+        self.benutzer.get(user_id)
+    }
+
+    pub fn kanal(&self, channel_id: &ChannelId) -> Option<&ResolvedChannel> {
+        self.kanaele.get(channel_id)
+    }
+
+    /// Anzeigename eines Benutzers (leerer Anzeigename = Benutzername)
+    pub fn anzeigename(&self, user_id: &UserId) -> Option<&str> {
+        self.benutzer.get(user_id).map(|b| {
+            if b.display_name.is_empty() {
+                b.username.as_str()
+            } else {
+                b.display_name.as_str()
+            }
+        })
+    }
+
+    pub fn kanalname(&self, channel_id: &ChannelId) -> Option<&str> {
+        self.kanaele.get(channel_id).map(|k| k.name.as_str())
+    }
+
+    /// Verwirft alle Namen (Verbindung getrennt)
+    pub fn leeren(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Uebernimmt Namen aus einer Server-Nachricht
+    ///
+    /// Gibt `true` zurueck wenn die Nachricht Namen enthielt. Andere
+    /// Nachrichten werden ignoriert.
+    pub fn anwenden(&mut self, payload: &ControlPayload) -> bool {
+        match payload {
+            ControlPayload::ClientListResponse(antwort) => {
+                antwort.clients.iter().for_each(|c| self.client_merken(c));
+            }
+            ControlPayload::ChannelMembersResponse(antwort) => {
+                antwort.clients.iter().for_each(|c| self.client_merken(c));
+            }
+            ControlPayload::ClientConnected(ereignis) => self.client_merken(&ereignis.client),
+            ControlPayload::ClientJoinedChannel(ereignis) => self.client_merken(&ereignis.client),
+            ControlPayload::ClientUpdated(ereignis) => self.client_merken(&ereignis.client),
+            ControlPayload::ChannelListResponse(antwort) => {
+                antwort.channels.iter().for_each(|k| self.kanal_merken(k));
+            }
+            ControlPayload::ChannelEdited(ereignis) => self.kanal_merken(&ereignis.channel),
+            ControlPayload::ResolveIdsResponse(antwort) => self.aufloesung_uebernehmen(antwort),
+            _ => return false,
+        }
+        true
+    }
+
+    /// Uebernimmt die Antwort auf `ResolveIds`
+    pub fn aufloesung_uebernehmen(&mut self, antwort: &ResolveIdsResponse) {
+        for benutzer in &antwort.users {
+            self.benutzer.insert(benutzer.user_id, benutzer.clone());
+        }
+        for kanal in &antwort.channels {
+            self.kanaele.insert(kanal.channel_id, kanal.clone());
+        }
+    }
+
+    /// `ResolveIds`-Anfragen fuer alle unbekannten IDs
+    ///
+    /// Doppelte IDs werden einmal angefragt. Jede Anfrage enthaelt hoechstens
+    /// [`MAX_AUFLOESUNG_IDS`] IDs; leer wenn alle Namen bekannt sind.
+    pub fn fehlende(
+        &self,
+        user_ids: impl IntoIterator<Item = UserId>,
+        channel_ids: impl IntoIterator<Item = ChannelId>,
+    ) -> Vec<ResolveIdsRequest> {
+        let mut gesehen = HashSet::new();
+        let benutzer: Vec<UserId> = user_ids
+            .into_iter()
+            .filter(|id| !self.benutzer.contains_key(id) && gesehen.insert(*id))
+            .collect();
+        let mut gesehen = HashSet::new();
+        let kanaele: Vec<ChannelId> = channel_ids
+            .into_iter()
+            .filter(|id| !self.kanaele.contains_key(id) && gesehen.insert(*id))
+            .collect();
+
+        let mut anfragen = Vec::new();
+        let mut aktuell = ResolveIdsRequest::default();
+        let mut platz = MAX_AUFLOESUNG_IDS;
+        for user_id in benutzer {
+            if platz == 0 {
+                anfragen.push(std::mem::take(&mut aktuell));
+                platz = MAX_AUFLOESUNG_IDS;
+            }
+            aktuell.user_ids.push(user_id);
+            platz -= 1;
+        }
+        for channel_id in kanaele {
+            if platz == 0 {
+                anfragen.push(std::mem::take(&mut aktuell));
+                platz = MAX_AUFLOESUNG_IDS;
+            }
+            aktuell.channel_ids.push(channel_id);
+            platz -= 1;
+        }
+        if platz < MAX_AUFLOESUNG_IDS {
+            anfragen.push(aktuell);
+        }
+        anfragen
+    }
+
+    fn client_merken(&mut self, client: &ClientInfo) {
+        // Soundboard-Wiedergaben tragen eine Wiedergabe-ID statt einer Benutzer-ID
+        if client.soundboard {
+            return;
+        }
+        self.benutzer.insert(
+            client.user_id,
+            ResolvedUser {
+                user_id: client.user_id,
+                username: client.username.clone(),
+                display_name: client.display_name.clone(),
+                deleted: false,
+            },
+        );
+    }
+
+    fn kanal_merken(&mut self, kanal: &ChannelInfo) {
+        self.kanaele.insert(
+            kanal.channel_id,
+            ResolvedChannel {
+                channel_id: kanal.channel_id,
+                name: kanal.name.clone(),
+                deleted: false,
+            },
+        );
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::control::{ChannelEditedEvent, ClientListResponse, ClientUpdatedEvent};
+    use uuid::Uuid;
+
+    fn benutzer_id(n: u128) -> UserId {
+        UserId(Uuid::from_u128(n))
+    }
+
+    fn kanal_id(n: u128) -> ChannelId {
+        ChannelId(Uuid::from_u128(n))
+    }
+
+    fn client(n: u128, display_name: &str) -> ClientInfo {
+        ClientInfo {
+            user_id: benutzer_id(n),
+            username: format!("user{n}"),
+            display_name: display_name.into(),
+            channel_id: None,
+            server_groups: vec![],
+            is_muted: false,
+            is_deafened: false,
+            is_input_muted: false,
+            ssrc: None,
+            listen_only: false,
+            soundboard: false,
+        }
+    }
+
+    fn kanal(n: u128, name: &str) -> ChannelInfo {
+        ChannelInfo {
+            channel_id: kanal_id(n),
+            name: name.into(),
+            description: None,
+            parent_id: None,
+            sort_order: 0,
+            max_clients: None,
+            current_clients: 0,
+            password_protected: false,
+            codec: "opus".into(),
+            codec_quality: 7,
+            has_children: false,
+            child_count: 0,
+            slow_mode_secs: 0,
+            edit_window_secs: 0,
+            join_by_approval: false,
+        }
+    }
+
+    #[test]
+    fn umbenennung_ersetzt_den_namen() {
+        let mut cache = NamensCache::neu();
+        assert!(
+            cache.anwenden(&ControlPayload::ClientListResponse(ClientListResponse {
+                clients: vec![client(1, "Anna"), client(2, "")],
+                state_version: 0,
+            }))
+        );
+        assert_eq!(cache.anzeigename(&benutzer_id(1)), Some("Anna"));
+        assert_eq!(cache.anzeigename(&benutzer_id(2)), Some("user2"));
+
+        assert!(
+            cache.anwenden(&ControlPayload::ClientUpdated(ClientUpdatedEvent {
+                client: client(1, "Anna B."),
+            }))
+        );
+        assert_eq!(cache.anzeigename(&benutzer_id(1)), Some("Anna B."));
+
+        cache.anwenden(&ControlPayload::ChannelListResponse(
+            crate::control::ChannelListResponse {
+                channels: vec![kanal(1, "Lobby")],
+                partial: false,
+            },
+        ));
+        assert!(
+            cache.anwenden(&ControlPayload::ChannelEdited(ChannelEditedEvent {
+                channel: kanal(1, "Eingang"),
+            }))
+        );
+        assert_eq!(cache.kanalname(&kanal_id(1)), Some("Eingang"));
+        assert!(cache.fehlende([benutzer_id(1)], [kanal_id(1)]).is_empty());
+    }
+
+    #[test]
+    fn geloeschte_benutzer_kommen_als_platzhalter() {
+        let mut cache = NamensCache::neu();
+        let anfragen = cache.fehlende([benutzer_id(7)], []);
+        assert_eq!(anfragen.len(), 1);
+        assert_eq!(anfragen[0].user_ids, [benutzer_id(7)]);
+
+        assert!(
+            cache.anwenden(&ControlPayload::ResolveIdsResponse(ResolveIdsResponse {
+                users: vec![ResolvedUser {
+                    user_id: benutzer_id(7),
+                    username: "[geloescht]".into(),
+                    display_name: "[geloescht]".into(),
+                    deleted: true,
+                }],
+                channels: vec![],
+            }))
+        );
+        assert!(cache.benutzer(&benutzer_id(7)).unwrap().deleted);
+        assert_eq!(cache.anzeigename(&benutzer_id(7)), Some("[geloescht]"));
+        // Einmal aufgeloest wird nicht erneut gefragt
+        assert!(cache.fehlende([benutzer_id(7)], []).is_empty());
+    }
+
+    #[test]
+    fn fehlende_ids_werden_entdoppelt_und_aufgeteilt() {
+        let mut cache = NamensCache::neu();
+        cache.anwenden(&ControlPayload::ClientUpdated(ClientUpdatedEvent {
+            client: client(0, "bekannt"),
+        }));
+        let benutzer = (0..=MAX_AUFLOESUNG_IDS as u128).chain([1, 2]);
+        let anfragen = cache.fehlende(benutzer.map(benutzer_id), [kanal_id(1), kanal_id(1)]);
+
+        assert_eq!(anfragen.len(), 2);
+        assert_eq!(anfragen[0].user_ids.len(), MAX_AUFLOESUNG_IDS);
+        assert!(anfragen[0].channel_ids.is_empty());
+        assert!(!anfragen[0].user_ids.contains(&benutzer_id(0)));
+        assert_eq!(anfragen[1].user_ids, Vec::<UserId>::new());
+        assert_eq!(anfragen[1].channel_ids, [kanal_id(1)]);
+    }
+
+    #[test]
+    fn soundboard_wiedergaben_werden_nicht_gemerkt() {
+        let mut cache = NamensCache::neu();
+        let mut wiedergabe = client(5, "Tusch");
+        wiedergabe.soundboard = true;
+        cache.anwenden(&ControlPayload::ClientUpdated(ClientUpdatedEvent {
+            client: wiedergabe,
+        }));
+        assert_eq!(cache.anzeigename(&benutzer_id(5)), None);
+    }
+}
@@ -0,0 +1,292 @@
+//! Presence-Abbild auf Client-Seite
+//!
+//! Haelt fest, welcher Benutzer in welchem Kanal ist, zusammen mit der
+//! Zustandsversion des Servers, auf der das Abbild beruht:
+//!
+//! - [`PresenzAbbild::liste_uebernehmen`] setzt das Abbild aus `ClientList`
+//! - [`PresenzAbbild::anwenden`] uebernimmt versionierte Ereignisse
+//! - [`PresenzAbbild::diff_anwenden`] holt nach einer Luecke (Standby,
+//!   kurze Trennung) die Ereignisse aus `StateDiff` nach
+//!
+//! Die Ereignisse setzen Zustand (Benutzer X ist jetzt in Kanal Y). Ein
+//! bereits im Abbild enthaltenes Ereignis erneut anzuwenden aendert nichts.
+
+use std::collections::HashMap;
+
+use speakeasy_core::types::{ChannelId, UserId};
+
+use crate::control::{ClientListResponse, ControlPayload, StateDiffResponse};
+
+/// Kanal je Benutzer und Zustandsversion
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PresenzAbbild {
+    version: u64,
+    kanaele: HashMap<UserId, Option<ChannelId>>,
+}
+
+impl PresenzAbbild {
+    pub fn neu() -> Self {
+        Self::default()
+    }
+
+    /// Zustandsversion, bis zu der das Abbild aktuell ist
+    pub fn version(&self) -> u64 {
+        self.version
+    }
+
+    /// Kanal eines Benutzers (None = ohne Kanal oder unbekannt)
+    pub fn kanal_von(&self, user_id: &UserId) -> Option<ChannelId> {
+        self.kanaele.get(user_id).copied().flatten()
+    }
+
+    /// Alle bekannten Benutzer in beliebiger Reihenfolge
+    pub fn benutzer(&self) -> impl Iterator<Item = (UserId, Option<ChannelId>)> + '_ {
+        self.kanaele
+            .iter()
+            .map(|(user_id, kanal)| (*user_id, *kanal))
+    }
+
+    pub fn len(&self) -> usize {
+        self.kanaele.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.kanaele.is_empty()
+    }
+
+    /// Verwirft das Abbild (Verbindung getrennt)
+    pub fn leeren(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Ersetzt das Abbild durch die Antwort auf `ClientList`
+    pub fn liste_uebernehmen(&mut self, antwort: &ClientListResponse) {
+        self.kanaele = antwort
+            .clients
+            .iter()
+            .map(|c| (c.user_id, c.channel_id))
+            .collect();
+        self.version = antwort.state_version;
+    }
+
+    /// Fehlt vor diesem Ereignis mindestens eines?
+    ///
+    /// Dann sollte der Client per `StateDiff` ab [`Self::version`] nachholen.
+    pub fn luecke_vor(&self, payload: &ControlPayload) -> bool {
+        match ereignis_version(payload) {
+            Some(version) if version > 0 => version > self.version.saturating_add(1),
+            _ => false,
+        }
+    }
+
+    /// Uebernimmt ein Presence-Ereignis des Servers
+    ///
+    /// Gibt `true` zurueck wenn die Nachricht ein Presence-Ereignis war.
+    /// Andere Nachrichten werden ignoriert.
+    pub fn anwenden(&mut self, payload: &ControlPayload) -> bool {
+        match payload {
+            ControlPayload::ClientMoved(ereignis) => {
+                self.kanaele
+                    .insert(ereignis.user_id, Some(ereignis.to_channel_id));
+            }
+            ControlPayload::ClientsMoved(ereignis) => {
+                for user_id in &ereignis.user_ids {
+                    self.kanaele.insert(*user_id, Some(ereignis.to_channel_id));
+                }
+            }
+            ControlPayload::ClientConnected(ereignis) => {
+                self.kanaele
+                    .insert(ereignis.client.user_id, ereignis.client.channel_id);
+            }
+            ControlPayload::ClientJoinedChannel(ereignis) => {
+                self.kanaele
+                    .insert(ereignis.client.user_id, Some(ereignis.channel_id));
+            }
+            ControlPayload::ClientLeftChannel(ereignis) => {
+                self.kanaele.insert(ereignis.client.user_id, None);
+            }
+            ControlPayload::ClientDisconnected(ereignis) => {
+                self.kanaele.remove(&ereignis.client.user_id);
+            }
+            _ => return false,
+        }
+        if let Some(version) = ereignis_version(payload) {
+            self.version = self.version.max(version);
+        }
+        true
+    }
+
+    /// Wendet die nachgeholten Ereignisse einer `StateDiffResponse` an
+    ///
+    /// Gibt `false` zurueck, wenn der Server ein neues Abbild verlangt; das
+    /// bisherige bleibt dann unveraendert.
+    pub fn diff_anwenden(
+        &mut self,
+        antwort: &StateDiffResponse,
+    ) -> Result<bool, serde_json::Error> {
+        if antwort.snapshot_required {
+            return Ok(false);
+        }
+        for nachricht in antwort.ereignisse()? {
+            self.anwenden(&nachricht.payload);
+        }
+        self.version = self.version.max(antwort.current_version);
+        Ok(true)
+    }
+}
+
+fn ereignis_version(payload: &ControlPayload) -> Option<u64> {
+    match payload {
+        ControlPayload::ClientMoved(ereignis) => Some(ereignis.state_version),
+        ControlPayload::ClientsMoved(ereignis) => Some(ereignis.state_version),
+        ControlPayload::ClientConnected(ereignis) => Some(ereignis.state_version),
+        ControlPayload::ClientDisconnected(ereignis) => Some(ereignis.state_version),
+        ControlPayload::ClientJoinedChannel(ereignis) => Some(ereignis.state_version),
+        ControlPayload::ClientLeftChannel(ereignis) => Some(ereignis.state_version),
+        _ => None,
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::control::{
+        ClientDisconnectedEvent, ClientInfo, ClientJoinedChannelEvent, ClientLeftChannelEvent,
+        ClientMovedEvent, ClientsMovedEvent, ControlMessage,
+    };
+    use uuid::Uuid;
+
+    fn user(n: u128) -> UserId {
+        UserId(Uuid::from_u128(n))
+    }
+
+    fn kanal(n: u128) -> ChannelId {
+        ChannelId(Uuid::from_u128(0x100 + n))
+    }
+
+    fn liste(clients: &[(u128, Option<u128>)], state_version: u64) -> ClientListResponse {
+        ClientListResponse {
+            clients: clients
+                .iter()
+                .map(|(n, k)| ClientInfo {
+                    user_id: user(*n),
+                    username: format!("user{n}"),
+                    display_name: format!("User {n}"),
+                    channel_id: k.map(kanal),
+                    server_groups: vec![],
+                    is_muted: false,
+                    is_deafened: false,
+                    is_input_muted: false,
+                    ssrc: None,
+                    listen_only: false,
+                    soundboard: false,
+                })
+                .collect(),
+            state_version,
+        }
+    }
+
+    fn verschoben(n: u128, nach: u128, state_version: u64) -> ControlPayload {
+        ControlPayload::ClientMoved(ClientMovedEvent {
+            user_id: user(n),
+            from_channel_id: None,
+            to_channel_id: kanal(nach),
+            reason: None,
+            state_version,
+        })
+    }
+
+    #[test]
+    fn ereignisse_fuehren_version_nach() {
+        let mut abbild = PresenzAbbild::neu();
+        abbild.liste_uebernehmen(&liste(&[(1, Some(1)), (2, None)], 10));
+        assert_eq!(abbild.version(), 10);
+
+        assert!(!abbild.luecke_vor(&verschoben(2, 1, 11)));
+        assert!(abbild.anwenden(&verschoben(2, 1, 11)));
+        assert_eq!(abbild.kanal_von(&user(2)), Some(kanal(1)));
+        assert_eq!(abbild.version(), 11);
+
+        assert!(abbild.luecke_vor(&verschoben(1, 2, 13)));
+        assert!(!abbild.anwenden(&ControlPayload::ClientActivity));
+    }
+
+    #[test]
+    fn diff_ergibt_dasselbe_abbild() {
+        let mut abbild = PresenzAbbild::neu();
+        abbild.liste_uebernehmen(&liste(&[(1, Some(1)), (2, Some(1)), (3, None)], 5));
+
+        let sammel = ControlPayload::ClientsMoved(ClientsMovedEvent {
+            user_ids: vec![user(1), user(2)],
+            from_channel_id: kanal(1),
+            to_channel_id: kanal(2),
+            reason: None,
+            state_version: 6,
+        });
+        let diff = StateDiffResponse {
+            current_version: 7,
+            snapshot_required: false,
+            events: [sammel, verschoben(3, 1, 7)]
+                .into_iter()
+                .map(|p| ControlMessage::new(0, p).to_json().unwrap())
+                .collect(),
+        };
+        assert!(abbild.diff_anwenden(&diff).unwrap());
+
+        let mut neu = PresenzAbbild::neu();
+        neu.liste_uebernehmen(&liste(&[(1, Some(2)), (2, Some(2)), (3, Some(1))], 7));
+        assert_eq!(abbild, neu);
+    }
+
+    #[test]
+    fn beitritt_verlassen_und_trennung() {
+        let mut abbild = PresenzAbbild::neu();
+        let mut antwort = liste(&[(1, Some(1)), (2, None)], 3);
+        abbild.liste_uebernehmen(&antwort);
+        let client = antwort.clients.remove(1);
+
+        assert!(abbild.anwenden(&ControlPayload::ClientJoinedChannel(
+            ClientJoinedChannelEvent {
+                client: client.clone(),
+                channel_id: kanal(1),
+                state_version: 4,
+            }
+        )));
+        assert_eq!(abbild.kanal_von(&user(2)), Some(kanal(1)));
+
+        assert!(
+            abbild.anwenden(&ControlPayload::ClientLeftChannel(ClientLeftChannelEvent {
+                client: client.clone(),
+                channel_id: kanal(1),
+                state_version: 5,
+            }))
+        );
+        assert!(abbild.anwenden(&ControlPayload::ClientDisconnected(
+            ClientDisconnectedEvent {
+                client,
+                state_version: 6,
+            }
+        )));
+        assert_eq!(abbild.len(), 1);
+        assert_eq!(abbild.version(), 6);
+    }
+
+    #[test]
+    fn abbild_verlangt_bleibt_unveraendert() {
+        let mut abbild = PresenzAbbild::neu();
+        abbild.liste_uebernehmen(&liste(&[(1, Some(1))], 5));
+        let vorher = abbild.clone();
+
+        let diff = StateDiffResponse {
+            current_version: 900,
+            snapshot_required: true,
+            events: Vec::new(),
+        };
+        assert!(!abbild.diff_anwenden(&diff).unwrap());
+        assert_eq!(abbild, vorher);
+    }
+}
@@ -0,0 +1,212 @@
+//! DSCP/QoS-Markierung fuer Sockets
+//!
+//! Setzt das DiffServ-Feld (IP_TOS bzw. IPV6_TCLASS) auf Voice- und
+//! Control-Sockets, damit Router in verwalteten Netzen Sprachpakete
+//! bevorzugt weiterleiten. Die Markierung ist best-effort: lehnt das
+//! Betriebssystem sie ab (z.B. Windows ohne QoS-Richtlinie), laeuft die
+//! Verbindung unmarkiert weiter und der Status wird als `Abgelehnt` gemeldet.
+
+use serde::{Deserialize, Serialize};
+
+pub use socket2::SockRef;
+
+/// Expedited Forwarding – Standardklasse fuer Sprachpakete
+pub const DSCP_EF: u8 = 46;
+/// Assured Forwarding 31 – niedrigere Prioritaet fuer Control-Traffic
+pub const DSCP_AF31: u8 = 26;
+/// Groesster gueltiger DSCP-Wert (6 Bit)
+pub const DSCP_MAX: u8 = 63;
+
+/// Wandelt einen DSCP-Wert in das ToS-/Traffic-Class-Byte um
+///
+/// Die unteren zwei Bit (ECN) bleiben frei.
+pub fn tos_aus_dscp(dscp: u8) -> u32 {
+    (dscp as u32) << 2
+}
+
+/// Ergebnis der QoS-Markierung eines Sockets
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum QosStatus {
+    /// Keine Markierung konfiguriert
+    Deaktiviert,
+    /// Markierung ist aktiv
+    Aktiv { dscp: u8 },
+    /// Markierung konfiguriert, aber vom System abgelehnt
+    Abgelehnt { dscp: u8, grund: String },
+}
+
+impl QosStatus {
+    /// Gibt true zurueck wenn ausgehende Pakete markiert werden
+    pub fn ist_aktiv(&self) -> bool {
+        matches!(self, Self::Aktiv { .. })
+    }
+}
+
+/// Markiert einen Socket mit dem angegebenen DSCP-Wert
+///
+/// Die Adressfamilie wird aus der lokalen Adresse des Sockets ermittelt.
+/// `None` laesst den Socket unveraendert.
+pub fn markieren(socket: SockRef<'_>, dscp: Option<u8>) -> QosStatus {
+    let Some(dscp) = dscp else {
+        return QosStatus::Deaktiviert;
+    };
+    match dscp_setzen(&socket, dscp) {
+        Ok(()) => QosStatus::Aktiv { dscp },
+        Err(e) => QosStatus::Abgelehnt {
+            dscp,
+            grund: e.to_string(),
+        },
+    }
+}
+
+fn dscp_setzen(socket: &SockRef<'_>, dscp: u8) -> std::io::Result<()> {
+    if dscp > DSCP_MAX {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            format!("DSCP-Wert {dscp} ausserhalb von 0..={DSCP_MAX}"),
+        ));
+    }
+    let ipv6 = socket.local_addr()?.is_ipv6();
+    if ipv6 {
+        tclass_setzen(socket, tos_aus_dscp(dscp))
+    } else {
+        tos_setzen(socket, tos_aus_dscp(dscp))
+    }
+}
+
+#[cfg(not(any(
+    target_os = "fuchsia",
+    target_os = "redox",
+    target_os = "solaris",
+    target_os = "illumos",
+    target_os = "haiku",
+)))]
+fn tos_setzen(socket: &SockRef<'_>, tos: u32) -> std::io::Result<()> {
+    socket.set_tos_v4(tos)
+}
+
+#[cfg(any(
+    target_os = "fuchsia",
+    target_os = "redox",
+    target_os = "solaris",
+    target_os = "illumos",
+    target_os = "haiku",
+))]
+fn tos_setzen(_socket: &SockRef<'_>, _tos: u32) -> std::io::Result<()> {
+    Err(nicht_unterstuetzt("IP_TOS"))
+}
+
+#[cfg(any(
+    target_os = "android",
+    target_os = "dragonfly",
+    target_os = "freebsd",
+    target_os = "fuchsia",
+    target_os = "linux",
+    target_os = "macos",
+    target_os = "netbsd",
+    target_os = "openbsd",
+))]
+fn tclass_setzen(socket: &SockRef<'_>, tclass: u32) -> std::io::Result<()> {
+    socket.set_tclass_v6(tclass)
+}
+
+#[cfg(not(any(
+    target_os = "android",
+    target_os = "dragonfly",
+    target_os = "freebsd",
+    target_os = "fuchsia",
+    target_os = "linux",
+    target_os = "macos",
+    target_os = "netbsd",
+    target_os = "openbsd",
+)))]
+fn tclass_setzen(_socket: &SockRef<'_>, _tclass: u32) -> std::io::Result<()> {
+    Err(nicht_unterstuetzt("IPV6_TCLASS"))
+}
+
+#[cfg(any(
+    target_os = "fuchsia",
+    target_os = "redox",
+    target_os = "solaris",
+    target_os = "illumos",
+    target_os = "haiku",
+    not(any(
+        target_os = "android",
+        target_os = "dragonfly",
+        target_os = "freebsd",
+        target_os = "fuchsia",
+        target_os = "linux",
+        target_os = "macos",
+        target_os = "netbsd",
+        target_os = "openbsd",
+    )),
+))]
+fn nicht_unterstuetzt(option: &str) -> std::io::Error {
+    std::io::Error::new(
+        std::io::ErrorKind::Unsupported,
+        format!("{option} wird auf dieser Plattform nicht unterstuetzt"),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tos_byte_laesst_ecn_frei() {
+        assert_eq!(tos_aus_dscp(DSCP_EF), 0xB8);
+        assert_eq!(tos_aus_dscp(DSCP_AF31), 0x68);
+    }
+
+    #[test]
+    fn ohne_dscp_deaktiviert() {
+        let socket = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+        let status = markieren(SockRef::from(&socket), None);
+        assert_eq!(status, QosStatus::Deaktiviert);
+        assert!(!status.ist_aktiv());
+    }
+
+    #[test]
+    fn ungueltiger_dscp_abgelehnt() {
+        let socket = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+        let status = markieren(SockRef::from(&socket), Some(64));
+        assert!(matches!(status, QosStatus::Abgelehnt { dscp: 64, .. }));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn udp_loopback_v4_markiert() {
+        let socket = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+        let status = markieren(SockRef::from(&socket), Some(DSCP_EF));
+        assert_eq!(status, QosStatus::Aktiv { dscp: DSCP_EF });
+        assert_eq!(SockRef::from(&socket).tos_v4().unwrap(), 0xB8);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn udp_loopback_v6_markiert() {
+        // IPv6 ist nicht in jeder CI-Umgebung verfuegbar
+        let Ok(socket) = std::net::UdpSocket::bind("[::1]:0") else {
+            return;
+        };
+        let status = markieren(SockRef::from(&socket), Some(DSCP_EF));
+        assert_eq!(status, QosStatus::Aktiv { dscp: DSCP_EF });
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn tcp_loopback_markiert() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let stream = std::net::TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        let status = markieren(SockRef::from(&stream), Some(DSCP_AF31));
+        assert!(status.ist_aktiv());
+    }
+
+    #[test]
+    fn status_serialisierung() {
+        let json = serde_json::to_value(QosStatus::Aktiv { dscp: 46 }).unwrap();
+        assert_eq!(json["status"], "aktiv");
+        assert_eq!(json["dscp"], 46);
+    }
+}
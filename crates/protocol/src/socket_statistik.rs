@@ -0,0 +1,219 @@
+//! Socket-Puffer und Drop-Zaehler fuer UDP-Sockets
+//!
+//! Kommt die Empfangsschleife nicht hinterher, laeuft der Empfangspuffer des
+//! Sockets voll und der Kernel verwirft weitere Datagramme, ohne dass die
+//! Anwendung davon erfaehrt. Der Verlust erscheint dann in der Telemetrie
+//! faelschlich als Netzwerkverlust. Dieses Modul setzt die Puffergroessen
+//! (SO_RCVBUF/SO_SNDBUF) und liest die Drop-Zaehler des Kernels.
+//!
+//! Unter Linux stammen die Zaehler aus `/proc/net/udp` bzw. `/proc/net/udp6`
+//! (Spalte `drops`). Das ist derselbe Zaehler, den `SO_RXQ_OVFL` liefert,
+//! nur ohne `recvmsg` mit Ancillary Data im Empfangspfad. Auf anderen
+//! Plattformen gibt [`zaehler_lesen`] `None` zurueck.
+
+use serde::{Deserialize, Serialize};
+
+use crate::qos::SockRef;
+
+/// Hinweis fuer Log-Meldungen, wenn der Kernel Datagramme verwirft
+pub const ABHILFE_HINWEIS: &str = "Empfangspuffer vergroessern (Konfiguration bzw. \
+     net.core.rmem_max unter Linux) oder CPU-Last der Empfangsschleife pruefen";
+
+/// Effektive Puffergroessen eines Sockets in Bytes
+///
+/// Das Betriebssystem darf die angeforderten Werte anpassen (Linux
+/// verdoppelt sie und begrenzt auf `net.core.rmem_max`/`wmem_max`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SocketPuffer {
+    pub empfang: usize,
+    pub senden: usize,
+}
+
+/// Setzt die Puffergroessen eines Sockets und liest die effektiven Werte zurueck
+///
+/// `None` laesst den jeweiligen Systemstandard unveraendert.
+pub fn puffer_setzen(
+    socket: SockRef<'_>,
+    empfang: Option<usize>,
+    senden: Option<usize>,
+) -> std::io::Result<SocketPuffer> {
+    if let Some(groesse) = empfang {
+        socket.set_recv_buffer_size(groesse)?;
+    }
+    if let Some(groesse) = senden {
+        socket.set_send_buffer_size(groesse)?;
+    }
+    Ok(SocketPuffer {
+        empfang: socket.recv_buffer_size()?,
+        senden: socket.send_buffer_size()?,
+    })
+}
+
+/// Momentaufnahme der Kernel-Zaehler eines UDP-Sockets
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SocketZaehler {
+    /// Belegte Bytes im Empfangspuffer
+    pub empfangs_warteschlange: u64,
+    /// Belegte Bytes im Sendepuffer
+    pub sende_warteschlange: u64,
+    /// Vom Kernel verworfene Datagramme seit dem Binden
+    pub verworfen: u64,
+}
+
+/// Liest die Kernel-Zaehler eines UDP-Sockets
+///
+/// Gibt `None` zurueck, wenn die Plattform keine Zaehler bereitstellt oder
+/// der Socket nicht gefunden wurde.
+#[cfg(target_os = "linux")]
+pub fn zaehler_lesen(socket: SockRef<'_>) -> Option<SocketZaehler> {
+    use std::os::fd::AsRawFd;
+    use std::os::unix::fs::MetadataExt;
+
+    // stat() auf den fd-Link liefert die Inode des Sockets
+    let inode = std::fs::metadata(format!("/proc/self/fd/{}", socket.as_raw_fd()))
+        .ok()?
+        .ino();
+    let datei = if socket.local_addr().ok()?.is_ipv6() {
+        "/proc/net/udp6"
+    } else {
+        "/proc/net/udp"
+    };
+    let inhalt = std::fs::read_to_string(datei).ok()?;
+    proc_net_udp_parsen(&inhalt, inode)
+}
+
+/// Liest die Kernel-Zaehler eines UDP-Sockets (auf dieser Plattform nicht verfuegbar)
+#[cfg(not(target_os = "linux"))]
+pub fn zaehler_lesen(_socket: SockRef<'_>) -> Option<SocketZaehler> {
+    None
+}
+
+/// Sucht die Zeile mit `inode` im Format von `/proc/net/udp` bzw. `udp6`
+///
+/// ```text
+///   sl  local_address rem_address   st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode ref pointer drops
+///    0: 0100007F:2703 00000000:0000 07 00000000:00000000 00:00000000 00000000  1000        0 31337 2 0000000000000000 0
+/// ```
+pub fn proc_net_udp_parsen(inhalt: &str, inode: u64) -> Option<SocketZaehler> {
+    inhalt.lines().skip(1).find_map(|zeile| {
+        let felder: Vec<&str> = zeile.split_whitespace().collect();
+        if felder.len() < 13 || felder[9].parse::<u64>().ok()? != inode {
+            return None;
+        }
+        let (tx, rx) = felder[4].split_once(':')?;
+        Some(SocketZaehler {
+            empfangs_warteschlange: u64::from_str_radix(rx, 16).ok()?,
+            sende_warteschlange: u64::from_str_radix(tx, 16).ok()?,
+            verworfen: felder[12].parse().ok()?,
+        })
+    })
+}
+
+/// Erkennt neu verworfene Datagramme zwischen zwei Abtastungen
+#[derive(Debug, Default)]
+pub struct DropErkennung {
+    letzter_stand: u64,
+}
+
+impl DropErkennung {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Gibt die Anzahl der seit der letzten Abtastung verworfenen Datagramme zurueck
+    pub fn neue_drops(&mut self, zaehler: &SocketZaehler) -> u64 {
+        let neu = zaehler.verworfen.saturating_sub(self.letzter_stand);
+        self.letzter_stand = zaehler.verworfen;
+        neu
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PROC_NET_UDP: &str = "\
+   sl  local_address rem_address   st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode ref pointer drops
+  123: 00000000:14E9 00000000:0000 07 00000000:00000000 00:00000000 00000000   102        0 17421 2 0000000000000000 0
+  467: 0100007F:2703 00000000:0000 07 00000200:0001A400 00:00000000 00000000  1000        0 31337 2 0000000000000000 42
+";
+
+    const PROC_NET_UDP6: &str = "\
+  sl  local_address                         remote_address                        st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode ref pointer drops
+ 1090: 00000000000000000000000001000000:2703 00000000000000000000000000000000:0000 07 00000000:00000A00 00:00000000 00000000  1000        0 9001 2 0000000000000000 7
+";
+
+    #[test]
+    fn proc_net_udp_zeile_per_inode() {
+        let zaehler = proc_net_udp_parsen(PROC_NET_UDP, 31337).unwrap();
+        assert_eq!(zaehler.empfangs_warteschlange, 0x1A400);
+        assert_eq!(zaehler.sende_warteschlange, 0x200);
+        assert_eq!(zaehler.verworfen, 42);
+
+        assert_eq!(
+            proc_net_udp_parsen(PROC_NET_UDP, 17421),
+            Some(SocketZaehler::default())
+        );
+        assert_eq!(proc_net_udp_parsen(PROC_NET_UDP, 1), None);
+    }
+
+    #[test]
+    fn proc_net_udp6_zeile() {
+        let zaehler = proc_net_udp_parsen(PROC_NET_UDP6, 9001).unwrap();
+        assert_eq!(zaehler.empfangs_warteschlange, 0xA00);
+        assert_eq!(zaehler.verworfen, 7);
+    }
+
+    #[test]
+    fn kaputte_zeilen_werden_ignoriert() {
+        let inhalt = "kopf\n  1: kaputt\n  2: a b c d e f g h i 5 k l nicht-zahl\n";
+        assert_eq!(proc_net_udp_parsen(inhalt, 5), None);
+        assert_eq!(proc_net_udp_parsen("", 5), None);
+    }
+
+    #[test]
+    fn drop_erkennung_meldet_nur_neue() {
+        let mut erkennung = DropErkennung::new();
+        let stand = |verworfen| SocketZaehler {
+            verworfen,
+            ..Default::default()
+        };
+        assert_eq!(erkennung.neue_drops(&stand(0)), 0);
+        assert_eq!(erkennung.neue_drops(&stand(5)), 5);
+        assert_eq!(erkennung.neue_drops(&stand(5)), 0);
+        assert_eq!(erkennung.neue_drops(&stand(12)), 7);
+    }
+
+    #[test]
+    fn puffer_werden_gesetzt() {
+        let socket = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+        let puffer =
+            puffer_setzen(SockRef::from(&socket), Some(32 * 1024), Some(48 * 1024)).unwrap();
+        // Linux verdoppelt den Wert fuer Verwaltungsdaten
+        assert!(puffer.empfang >= 32 * 1024);
+        assert!(puffer.senden >= 48 * 1024);
+        assert_eq!(
+            puffer.empfang,
+            SockRef::from(&socket).recv_buffer_size().unwrap()
+        );
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn zaehler_eines_echten_sockets() {
+        let socket = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+        let zaehler = zaehler_lesen(SockRef::from(&socket)).expect("Socket in /proc/net/udp");
+        assert_eq!(zaehler.verworfen, 0);
+
+        // Ohne Empfaenger laeuft ein minimaler Puffer ueber
+        puffer_setzen(SockRef::from(&socket), Some(1), None).unwrap();
+        let sender = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+        let ziel = socket.local_addr().unwrap();
+        for _ in 0..64 {
+            sender.send_to(&[0u8; 1000], ziel).unwrap();
+        }
+        let zaehler = zaehler_lesen(SockRef::from(&socket)).unwrap();
+        assert!(zaehler.verworfen > 0);
+        assert!(zaehler.empfangs_warteschlange > 0);
+    }
+}
@@ -0,0 +1,213 @@
+//! Sprechanzeige auf Client-Seite
+//!
+//! Die Mitgliederliste soll nach Sprechaktivitaet sortieren und hervorheben,
+//! auch fuer Benutzer, deren Pakete der Client selbst (noch) nicht empfangen
+//! hat. Der Zustand stammt daher aus Server-Nachrichten:
+//!
+//! - `ChannelJoinResponse` liefert die beim Beitritt sprechenden Mitglieder
+//! - `ClientSpeaking` meldet jeden (gedrosselten) Wechsel im Kanal
+//! - `ClientMoved`/`ClientsMoved` und `ClientLeftChannel` entfernen
+//!   Mitglieder, die den Kanal verlassen
+
+use std::collections::{HashMap, HashSet};
+
+use speakeasy_core::types::{ChannelId, UserId};
+
+use crate::control::{ClientSpeakingEvent, ControlPayload};
+
+/// Wer spricht im aktuellen Kanal und wann zuletzt
+#[derive(Debug, Clone, Default)]
+pub struct SprecherAnzeige {
+    kanal: Option<ChannelId>,
+    sprechend: HashSet<UserId>,
+    /// Zeitpunkt der letzten Sprechaktivitaet (Unix-Millisekunden)
+    zuletzt_ms: HashMap<UserId, u64>,
+}
+
+impl SprecherAnzeige {
+    pub fn neu() -> Self {
+        Self::default()
+    }
+
+    /// Kanal, auf den sich die Anzeige bezieht
+    pub fn kanal(&self) -> Option<ChannelId> {
+        self.kanal
+    }
+
+    /// Spricht der Benutzer gerade?
+    pub fn spricht(&self, user_id: &UserId) -> bool {
+        self.sprechend.contains(user_id)
+    }
+
+    /// Letzte Sprechaktivitaet eines Benutzers (Unix-Millisekunden)
+    ///
+    /// Waehrend der Benutzer spricht, ist das der Beginn der Sprechphase.
+    pub fn zuletzt_gesprochen_ms(&self, user_id: &UserId) -> Option<u64> {
+        self.zuletzt_ms.get(user_id).copied()
+    }
+
+    /// Verwirft die Anzeige (Kanal verlassen, Verbindung getrennt)
+    pub fn leeren(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Uebernimmt eine Server-Nachricht zum aktuellen Zeitpunkt
+    ///
+    /// Gibt `true` zurueck wenn sich die Anzeige geaendert haben kann.
+    pub fn anwenden(&mut self, payload: &ControlPayload) -> bool {
+        let jetzt_ms = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis() as u64;
+        self.anwenden_um(payload, jetzt_ms)
+    }
+
+    /// Wie [`Self::anwenden`], mit explizitem Zeitpunkt (Unix-Millisekunden)
+    pub fn anwenden_um(&mut self, payload: &ControlPayload, jetzt_ms: u64) -> bool {
+        match payload {
+            ControlPayload::ChannelJoinResponse(antwort) => {
+                self.leeren();
+                self.kanal = Some(antwort.channel_id);
+                for user_id in &antwort.speaking {
+                    self.sprechend.insert(*user_id);
+                    self.zuletzt_ms.insert(*user_id, jetzt_ms);
+                }
+                true
+            }
+            ControlPayload::ClientSpeaking(ereignis) => self.wechsel(ereignis, jetzt_ms),
+            ControlPayload::ClientMoved(ereignis)
+                if self.kanal.is_some() && ereignis.from_channel_id == self.kanal =>
+            {
+                self.entfernen(&ereignis.user_id);
+                true
+            }
+            ControlPayload::ClientsMoved(ereignis)
+                if self.kanal == Some(ereignis.from_channel_id) =>
+            {
+                for user_id in &ereignis.user_ids {
+                    self.entfernen(user_id);
+                }
+                true
+            }
+            ControlPayload::ClientLeftChannel(ereignis)
+                if self.kanal == Some(ereignis.channel_id) =>
+            {
+                self.entfernen(&ereignis.client.user_id);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn wechsel(&mut self, ereignis: &ClientSpeakingEvent, jetzt_ms: u64) -> bool {
+        if self.kanal != Some(ereignis.channel_id) {
+            return false;
+        }
+        if ereignis.speaking {
+            self.sprechend.insert(ereignis.user_id);
+        } else {
+            self.sprechend.remove(&ereignis.user_id);
+        }
+        self.zuletzt_ms.insert(ereignis.user_id, jetzt_ms);
+        true
+    }
+
+    fn entfernen(&mut self, user_id: &UserId) {
+        self.sprechend.remove(user_id);
+        self.zuletzt_ms.remove(user_id);
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::control::{ChannelJoinResponse, ClientMovedEvent};
+    use uuid::Uuid;
+
+    fn user(n: u128) -> UserId {
+        UserId(Uuid::from_u128(n))
+    }
+
+    fn kanal(n: u128) -> ChannelId {
+        ChannelId(Uuid::from_u128(0x100 + n))
+    }
+
+    fn beitritt(kanal_nr: u128, speaking: Vec<UserId>) -> ControlPayload {
+        ControlPayload::ChannelJoinResponse(ChannelJoinResponse {
+            channel_id: kanal(kanal_nr),
+            clients: vec![],
+            member_count: 1,
+            members_partial: false,
+            listen_only: false,
+            speaking,
+            slow_mode_secs: 0,
+        })
+    }
+
+    fn sprechen(n: u128, kanal_nr: u128, speaking: bool) -> ControlPayload {
+        ControlPayload::ClientSpeaking(ClientSpeakingEvent {
+            user_id: user(n),
+            channel_id: kanal(kanal_nr),
+            speaking,
+        })
+    }
+
+    #[test]
+    fn beitritt_uebernimmt_aktuelle_sprecher() {
+        let mut anzeige = SprecherAnzeige::neu();
+        assert!(anzeige.anwenden_um(&beitritt(1, vec![user(2)]), 1_000));
+        assert_eq!(anzeige.kanal(), Some(kanal(1)));
+        assert!(anzeige.spricht(&user(2)));
+        assert!(!anzeige.spricht(&user(3)));
+        assert_eq!(anzeige.zuletzt_gesprochen_ms(&user(2)), Some(1_000));
+        assert_eq!(anzeige.zuletzt_gesprochen_ms(&user(3)), None);
+    }
+
+    #[test]
+    fn wechsel_aktualisieren_zeitpunkt() {
+        let mut anzeige = SprecherAnzeige::neu();
+        anzeige.anwenden_um(&beitritt(1, vec![]), 0);
+
+        anzeige.anwenden_um(&sprechen(2, 1, true), 100);
+        assert!(anzeige.spricht(&user(2)));
+
+        anzeige.anwenden_um(&sprechen(2, 1, false), 900);
+        assert!(!anzeige.spricht(&user(2)));
+        assert_eq!(anzeige.zuletzt_gesprochen_ms(&user(2)), Some(900));
+    }
+
+    #[test]
+    fn fremde_kanaele_werden_ignoriert() {
+        let mut anzeige = SprecherAnzeige::neu();
+        anzeige.anwenden_um(&beitritt(1, vec![]), 0);
+        assert!(!anzeige.anwenden_um(&sprechen(2, 9, true), 100));
+        assert!(!anzeige.spricht(&user(2)));
+    }
+
+    #[test]
+    fn verlassen_entfernt_mitglied() {
+        let mut anzeige = SprecherAnzeige::neu();
+        anzeige.anwenden_um(&beitritt(1, vec![user(2)]), 0);
+        anzeige.anwenden_um(
+            &ControlPayload::ClientMoved(ClientMovedEvent {
+                user_id: user(2),
+                from_channel_id: Some(kanal(1)),
+                to_channel_id: kanal(2),
+                reason: None,
+                state_version: 0,
+            }),
+            100,
+        );
+        assert!(!anzeige.spricht(&user(2)));
+        assert_eq!(anzeige.zuletzt_gesprochen_ms(&user(2)), None);
+
+        // Neuer Kanal verwirft den alten Stand
+        anzeige.anwenden_um(&sprechen(3, 1, true), 200);
+        anzeige.anwenden_um(&beitritt(2, vec![]), 300);
+        assert!(!anzeige.spricht(&user(3)));
+    }
+}
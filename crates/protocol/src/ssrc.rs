@@ -0,0 +1,299 @@
+//! SSRC-Zuordnung auf Client-Seite
+//!
+//! Clients brauchen fuer Sprechanzeige und Lautstaerke pro Benutzer die
+//! Zuordnung eingehender SSRCs zu Benutzern. Diese wird ausschliesslich aus
+//! Server-Nachrichten gespeist – nie aus beobachteten Paketen erraten:
+//!
+//! - `ChannelJoinResponse` setzt die Zuordnung fuer den neuen Kanal
+//! - `ClientVoiceUpdated` aendert oder entfernt die SSRC eines Mitglieds
+//! - `ClientMoved`/`ClientsMoved` entfernen Mitglieder, die den Kanal verlassen
+//! - `SoundboardPlayback` ordnet die SSRC einer Soundboard-Wiedergabe deren
+//!   Pseudo-Client zu und entfernt sie nach dem Ende wieder
+
+use std::collections::HashMap;
+
+use speakeasy_core::types::{ChannelId, UserId};
+
+use crate::control::{
+    ChannelJoinResponse, ClientVoiceUpdatedEvent, ControlPayload, SoundboardPlaybackEvent,
+};
+
+/// SSRC -> Benutzer fuer den aktuellen Kanal (beide Richtungen eindeutig)
+#[derive(Debug, Clone, Default)]
+pub struct SsrcZuordnung {
+    kanal: Option<ChannelId>,
+    nach_ssrc: HashMap<u32, UserId>,
+    nach_user: HashMap<UserId, u32>,
+}
+
+impl SsrcZuordnung {
+    pub fn neu() -> Self {
+        Self::default()
+    }
+
+    /// Kanal, auf den sich die Zuordnung bezieht
+    pub fn kanal(&self) -> Option<ChannelId> {
+        self.kanal
+    }
+
+    /// Benutzer hinter einer SSRC
+    pub fn user_von_ssrc(&self, ssrc: u32) -> Option<UserId> {
+        self.nach_ssrc.get(&ssrc).copied()
+    }
+
+    /// Aktuelle SSRC eines Benutzers
+    pub fn ssrc_von_user(&self, user_id: &UserId) -> Option<u32> {
+        self.nach_user.get(user_id).copied()
+    }
+
+    /// Alle Zuordnungen (SSRC, Benutzer) in beliebiger Reihenfolge
+    pub fn eintraege(&self) -> impl Iterator<Item = (u32, UserId)> + '_ {
+        self.nach_ssrc
+            .iter()
+            .map(|(ssrc, user_id)| (*ssrc, *user_id))
+    }
+
+    /// Anzahl zugeordneter SSRCs
+    pub fn len(&self) -> usize {
+        self.nach_ssrc.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nach_ssrc.is_empty()
+    }
+
+    /// Verwirft die Zuordnung (Kanal verlassen, Verbindung getrennt)
+    pub fn leeren(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Uebernimmt eine Server-Nachricht
+    ///
+    /// Gibt `true` zurueck wenn sich die Zuordnung geaendert haben kann.
+    /// Andere Nachrichten werden ignoriert.
+    pub fn anwenden(&mut self, payload: &ControlPayload) -> bool {
+        match payload {
+            ControlPayload::ChannelJoinResponse(antwort) => {
+                self.kanal_beitreten(antwort);
+                true
+            }
+            ControlPayload::ClientVoiceUpdated(ereignis) => self.voice_aktualisiert(ereignis),
+            ControlPayload::SoundboardPlayback(ereignis) => self.wiedergabe(ereignis),
+            ControlPayload::ClientMoved(ereignis)
+                if self.kanal.is_some() && ereignis.from_channel_id == self.kanal =>
+            {
+                self.setzen(ereignis.user_id, None);
+                true
+            }
+            ControlPayload::ClientsMoved(ereignis)
+                if self.kanal == Some(ereignis.from_channel_id) =>
+            {
+                for user_id in &ereignis.user_ids {
+                    self.setzen(*user_id, None);
+                }
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn kanal_beitreten(&mut self, antwort: &ChannelJoinResponse) {
+        self.leeren();
+        self.kanal = Some(antwort.channel_id);
+        for client in &antwort.clients {
+            self.setzen(client.user_id, client.ssrc);
+        }
+    }
+
+    fn voice_aktualisiert(&mut self, ereignis: &ClientVoiceUpdatedEvent) -> bool {
+        if self.kanal != Some(ereignis.channel_id) {
+            return false;
+        }
+        self.setzen(ereignis.user_id, ereignis.ssrc);
+        true
+    }
+
+    fn wiedergabe(&mut self, ereignis: &SoundboardPlaybackEvent) -> bool {
+        if self.kanal != Some(ereignis.channel_id) {
+            return false;
+        }
+        let ssrc = ereignis.client.ssrc.filter(|_| ereignis.active);
+        self.setzen(ereignis.client.user_id, ssrc);
+        true
+    }
+
+    /// Setzt die SSRC eines Benutzers; eine bisherige Zuordnung derselben
+    /// SSRC zu einem anderen Benutzer wird dabei aufgehoben
+    fn setzen(&mut self, user_id: UserId, ssrc: Option<u32>) {
+        if let Some(alt) = self.nach_user.remove(&user_id) {
+            self.nach_ssrc.remove(&alt);
+        }
+        let Some(ssrc) = ssrc else {
+            return;
+        };
+        if let Some(vorheriger) = self.nach_ssrc.insert(ssrc, user_id) {
+            self.nach_user.remove(&vorheriger);
+        }
+        self.nach_user.insert(user_id, ssrc);
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::control::{
+        ClientInfo, ClientMovedEvent, ClientsMovedEvent, SoundboardPlaybackEvent,
+    };
+    use uuid::Uuid;
+
+    fn user(n: u128) -> UserId {
+        UserId(Uuid::from_u128(n))
+    }
+
+    fn kanal(n: u128) -> ChannelId {
+        ChannelId(Uuid::from_u128(0x100 + n))
+    }
+
+    fn client(n: u128, ssrc: Option<u32>) -> ClientInfo {
+        ClientInfo {
+            user_id: user(n),
+            username: format!("user{n}"),
+            display_name: format!("User {n}"),
+            channel_id: Some(kanal(1)),
+            server_groups: vec![],
+            is_muted: false,
+            is_deafened: false,
+            is_input_muted: false,
+            ssrc,
+            listen_only: false,
+            soundboard: false,
+        }
+    }
+
+    fn beitritt(clients: Vec<ClientInfo>) -> ControlPayload {
+        ControlPayload::ChannelJoinResponse(ChannelJoinResponse {
+            channel_id: kanal(1),
+            member_count: clients.len() as u32 + 1,
+            clients,
+            members_partial: false,
+            listen_only: false,
+            speaking: Vec::new(),
+            slow_mode_secs: 0,
+        })
+    }
+
+    fn voice(n: u128, kanal_nr: u128, ssrc: Option<u32>) -> ControlPayload {
+        ControlPayload::ClientVoiceUpdated(ClientVoiceUpdatedEvent {
+            user_id: user(n),
+            channel_id: kanal(kanal_nr),
+            ssrc,
+            listen_only: false,
+        })
+    }
+
+    #[test]
+    fn beitritt_setzt_zuordnung() {
+        let mut z = SsrcZuordnung::neu();
+        z.anwenden(&beitritt(vec![client(1, Some(10)), client(2, None)]));
+        assert_eq!(z.kanal(), Some(kanal(1)));
+        assert_eq!(z.user_von_ssrc(10), Some(user(1)));
+        assert_eq!(z.ssrc_von_user(&user(2)), None);
+        assert_eq!(z.len(), 1);
+    }
+
+    #[test]
+    fn ssrc_wechsel_mitten_in_der_sitzung() {
+        let mut z = SsrcZuordnung::neu();
+        z.anwenden(&beitritt(vec![client(1, Some(10))]));
+        assert!(z.anwenden(&voice(1, 1, Some(11))));
+
+        assert_eq!(z.user_von_ssrc(11), Some(user(1)));
+        assert_eq!(z.user_von_ssrc(10), None);
+        assert_eq!(z.len(), 1);
+    }
+
+    #[test]
+    fn neu_vergebene_ssrc_verdraengt_alten_inhaber() {
+        let mut z = SsrcZuordnung::neu();
+        z.anwenden(&beitritt(vec![client(1, Some(10)), client(2, Some(20))]));
+        z.anwenden(&voice(2, 1, Some(10)));
+
+        assert_eq!(z.user_von_ssrc(10), Some(user(2)));
+        assert_eq!(z.ssrc_von_user(&user(1)), None);
+        assert_eq!(z.user_von_ssrc(20), None);
+    }
+
+    #[test]
+    fn andere_kanaele_und_abgaenge() {
+        let mut z = SsrcZuordnung::neu();
+        // Ohne Kanal wird nichts uebernommen
+        assert!(!z.anwenden(&voice(1, 1, Some(10))));
+        assert!(z.is_empty());
+
+        z.anwenden(&beitritt(vec![client(1, Some(10)), client(2, Some(20))]));
+        assert!(!z.anwenden(&voice(3, 2, Some(30))));
+        assert_eq!(z.user_von_ssrc(30), None);
+
+        z.anwenden(&voice(1, 1, None));
+        assert_eq!(z.user_von_ssrc(10), None);
+
+        z.anwenden(&ControlPayload::ClientMoved(ClientMovedEvent {
+            user_id: user(2),
+            from_channel_id: Some(kanal(1)),
+            to_channel_id: kanal(2),
+            reason: None,
+            state_version: 0,
+        }));
+        assert!(z.is_empty());
+    }
+
+    #[test]
+    fn sammel_move_entfernt_alle_verschobenen() {
+        let mut z = SsrcZuordnung::default();
+        z.anwenden(&beitritt(vec![
+            client(1, Some(10)),
+            client(2, Some(20)),
+            client(3, Some(30)),
+        ]));
+
+        z.anwenden(&ControlPayload::ClientsMoved(ClientsMovedEvent {
+            user_ids: vec![user(1), user(3)],
+            from_channel_id: kanal(1),
+            to_channel_id: kanal(2),
+            reason: None,
+            state_version: 0,
+        }));
+        assert_eq!(z.user_von_ssrc(10), None);
+        assert_eq!(z.user_von_ssrc(20), Some(user(2)));
+        assert_eq!(z.user_von_ssrc(30), None);
+    }
+
+    #[test]
+    fn soundboard_wiedergabe_kommt_und_geht() {
+        let mut z = SsrcZuordnung::neu();
+        z.anwenden(&beitritt(vec![client(1, Some(10))]));
+        let wiedergabe = |kanal_nr, active| {
+            ControlPayload::SoundboardPlayback(SoundboardPlaybackEvent {
+                channel_id: kanal(kanal_nr),
+                sound_id: "fanfare".into(),
+                started_by: user(1),
+                client: ClientInfo {
+                    soundboard: true,
+                    ..client(9, Some(90))
+                },
+                active,
+            })
+        };
+
+        assert!(!z.anwenden(&wiedergabe(2, true)));
+        assert!(z.anwenden(&wiedergabe(1, true)));
+        assert_eq!(z.user_von_ssrc(90), Some(user(9)));
+        assert!(z.anwenden(&wiedergabe(1, false)));
+        assert_eq!(z.user_von_ssrc(90), None);
+        assert_eq!(z.user_von_ssrc(10), Some(user(1)));
+    }
+}
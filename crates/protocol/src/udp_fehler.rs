@@ -0,0 +1,142 @@
+//! Fehler in UDP-Empfangs- und Sendeschleifen
+//!
+//! Antwortet ein Ziel auf ein Datagramm mit ICMP "Port unreachable", meldet
+//! Windows das beim *naechsten* `recv_from` als `WSAECONNRESET` – unabhaengig
+//! davon, von wem das naechste Datagramm stammt. Linux meldet ICMP-Fehler nur
+//! auf verbundenen Sockets (`ECONNREFUSED`). Solche Fehler betreffen immer nur
+//! ein einzelnes Ziel und duerfen eine Empfangsschleife nie beenden.
+//!
+//! [`connreset_abschalten`] unterdrueckt das Windows-Verhalten direkt am
+//! Socket (`SIO_UDP_CONNRESET`, Feature `windows-connreset`); [`einordnen`]
+//! unterscheidet die Fehler, die trotzdem auftreten.
+
+use std::io;
+
+use crate::qos::SockRef;
+
+/// Einordnung eines Fehlers von `recv_from`/`send_to`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UdpFehlerArt {
+    /// ICMP-Rueckmeldung: ein Ziel ist (voruebergehend) nicht erreichbar
+    ZielUnerreichbar,
+    /// Unterbrechung ohne Bedeutung, sofort weitermachen
+    Voruebergehend,
+    /// Sonstiger Fehler: protokollieren, kurz warten, weitermachen
+    Sonstiger,
+}
+
+/// Ordnet einen Socket-Fehler ein
+pub fn einordnen(fehler: &io::Error) -> UdpFehlerArt {
+    match fehler.kind() {
+        io::ErrorKind::ConnectionReset
+        | io::ErrorKind::ConnectionRefused
+        | io::ErrorKind::HostUnreachable
+        | io::ErrorKind::NetworkUnreachable => UdpFehlerArt::ZielUnerreichbar,
+        io::ErrorKind::WouldBlock | io::ErrorKind::Interrupted | io::ErrorKind::TimedOut => {
+            UdpFehlerArt::Voruebergehend
+        }
+        _ => UdpFehlerArt::Sonstiger,
+    }
+}
+
+/// Schaltet die Meldung von ICMP-Fehlern an `recv_from` ab (nur Windows)
+///
+/// Gibt `true` zurueck wenn das Verhalten abgeschaltet wurde, `false` auf
+/// Plattformen ohne dieses Verhalten bzw. ohne Feature `windows-connreset`.
+pub fn connreset_abschalten(socket: SockRef<'_>) -> io::Result<bool> {
+    plattform::connreset_abschalten(socket)
+}
+
+#[cfg(all(windows, feature = "windows-connreset"))]
+mod plattform {
+    use std::os::windows::io::AsRawSocket;
+
+    use windows_sys::Win32::Networking::WinSock::{WSAGetLastError, WSAIoctl, SOCKET};
+
+    use super::SockRef;
+
+    /// `_WSAIOW(IOC_VENDOR, 12)`
+    const SIO_UDP_CONNRESET: u32 = 0x9800_000C;
+
+    pub(super) fn connreset_abschalten(socket: SockRef<'_>) -> std::io::Result<bool> {
+        let aus: u32 = 0;
+        let mut zurueck: u32 = 0;
+        // SAFETY: gueltiger Socket-Handle, Eingabepuffer lebt bis zum Ende des
+        // synchronen Aufrufs, kein Overlapped-I/O
+        let ergebnis = unsafe {
+            WSAIoctl(
+                socket.as_raw_socket() as SOCKET,
+                SIO_UDP_CONNRESET,
+                &aus as *const u32 as *const _,
+                std::mem::size_of::<u32>() as u32,
+                std::ptr::null_mut(),
+                0,
+                &mut zurueck,
+                std::ptr::null_mut(),
+                None,
+            )
+        };
+        if ergebnis != 0 {
+            return Err(std::io::Error::from_raw_os_error(unsafe {
+                WSAGetLastError()
+            }));
+        }
+        Ok(true)
+    }
+}
+
+#[cfg(not(all(windows, feature = "windows-connreset")))]
+mod plattform {
+    use super::SockRef;
+
+    pub(super) fn connreset_abschalten(_socket: SockRef<'_>) -> std::io::Result<bool> {
+        Ok(false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn icmp_rueckmeldungen_betreffen_nur_ein_ziel() {
+        for kind in [
+            io::ErrorKind::ConnectionReset,
+            io::ErrorKind::ConnectionRefused,
+            io::ErrorKind::HostUnreachable,
+            io::ErrorKind::NetworkUnreachable,
+        ] {
+            assert_eq!(
+                einordnen(&io::Error::from(kind)),
+                UdpFehlerArt::ZielUnerreichbar
+            );
+        }
+        assert_eq!(
+            einordnen(&io::Error::from(io::ErrorKind::Interrupted)),
+            UdpFehlerArt::Voruebergehend
+        );
+        assert_eq!(
+            einordnen(&io::Error::from(io::ErrorKind::PermissionDenied)),
+            UdpFehlerArt::Sonstiger
+        );
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn linux_fehlercodes() {
+        // ECONNREFUSED, EHOSTUNREACH
+        for code in [111, 113] {
+            assert_eq!(
+                einordnen(&io::Error::from_raw_os_error(code)),
+                UdpFehlerArt::ZielUnerreichbar
+            );
+        }
+    }
+
+    #[cfg(not(windows))]
+    #[test]
+    fn ohne_windows_nichts_abzuschalten() {
+        let socket = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+        assert!(!connreset_abschalten(SockRef::from(&socket)).unwrap());
+    }
+}
@@ -9,16 +9,55 @@
 //! Offset  Len  Beschreibung
 //! ------  ---  -----------
 //!  0       1   Version
-//!  1       1   PacketType (0 = Audio, 1 = Silence, 2 = FEC)
+//!  1       1   PacketType (0 = Audio, 1 = Silence, 2 = FEC, 3 = Ping, 4 = Hello)
 //!  2       2   Flags (big-endian)
 //!  4       4   SequenzNummer (big-endian)
 //!  8       4   Zeitstempel (big-endian, 48 kHz-Ticks)
 //! 12       4   SSRC – Synchronisation Source (big-endian)
 //! 16+      N   Nutzdaten (Opus-Bytes)
 //! ```
+//!
+//! ## Ping (Latenzmessung ohne Anmeldung)
+//!
+//! Anfrage und Antwort sind gleich gross ([`PING_GROESSE`]), damit der Server
+//! nicht als Verstaerker missbraucht werden kann. Sequenz und Zeitstempel
+//! gibt der Server unveraendert zurueck, die SSRC ist immer 0.
+//!
+//! ```text
+//! Offset  Len  Beschreibung
+//! ------  ---  -----------
+//!  0      16   Header (PacketType = 3, Flag PING_ANTWORT nur in der Antwort)
+//! 16       8   Serverzeit in ms seit UNIX-Epoche (big-endian, Anfrage: 0)
+//! ```
+//!
+//! ## Hello (Anmeldung des UDP-Endpunkts)
+//!
+//! Der Server kennt die UDP-Adresse eines Clients erst, wenn dieser sich
+//! damit meldet – hinter NAT weicht der Quellport von allem ab, was ueber
+//! TCP ausgehandelt wurde. Nach `VoiceReady` schickt der Client deshalb ein
+//! Hello mit seiner SSRC und einem HMAC-SHA256 ueber SSRC und Versuch,
+//! geschluesselt mit der Nonce aus `VoiceReadyResponse::hello_nonce`. Der
+//! Server merkt sich die beobachtete Absenderadresse und spiegelt das Hello
+//! (Flag HELLO_ANTWORT) gleich gross zurueck.
+//!
+//! Der Versuch beginnt je Nonce bei 0 und steigt mit jeder Wiederholung; der
+//! Server nimmt nur steigende Versuche an, ein mitgeschnittenes Hello laesst
+//! sich also nicht erneut einspielen. Einen bestaetigten Endpunkt verlegt erst
+//! ein Hello mit frischer Nonce (neuer `VoiceReady`).
+//!
+//! ```text
+//! Offset  Len  Beschreibung
+//! ------  ---  -----------
+//!  0      16   Header (PacketType = 4, Sequenz = Versuch, SSRC des Clients)
+//! 16      32   HMAC-SHA256(Nonce, "speakeasy-voice-hello" || SSRC || Versuch),
+//!              SSRC und Versuch je big-endian
+//! ```
 
 use std::io;
 
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
 /// Aktuelle Protokollversion
 pub const PROTOKOLL_VERSION: u8 = 1;
 
@@ -45,6 +84,12 @@ impl VoiceFlags {
     pub const SPEAKING_START: u16 = 0x0010;
     /// Ende einer Sprechsequenz
     pub const SPEAKING_STOP: u16 = 0x0020;
+    /// Nutzdaten sind PCMU (G.711 mu-law, 8 kHz) statt Opus
+    pub const PCMU: u16 = 0x0040;
+    /// Ping-Antwort des Servers (wird nie erneut beantwortet)
+    pub const PING_ANTWORT: u16 = 0x0080;
+    /// Bestaetigung eines Hellos durch den Server
+    pub const HELLO_ANTWORT: u16 = 0x0100;
 }
 
 // ---------------------------------------------------------------------------
@@ -61,6 +106,10 @@ pub enum PacketType {
     Silence = 1,
     /// Forward Error Correction Daten
     Fec = 2,
+    /// Latenzmessung, wird vom Server reflektiert und nie weitergeleitet
+    Ping = 3,
+    /// Anmeldung des UDP-Endpunkts, wird nie weitergeleitet
+    Hello = 4,
 }
 
 impl PacketType {
@@ -70,6 +119,8 @@ impl PacketType {
             0 => Some(Self::Audio),
             1 => Some(Self::Silence),
             2 => Some(Self::Fec),
+            3 => Some(Self::Ping),
+            4 => Some(Self::Hello),
             _ => None,
         }
     }
@@ -186,6 +237,40 @@ impl VoicePacketHeader {
     pub fn hat_flag(&self, flag: u16) -> bool {
         self.flags & flag != 0
     }
+
+    /// Ueberschreibt die Sequenznummer eines kodierten Pakets in-place
+    ///
+    /// Fuer die Weiterleitung: der Rest des Pakets (inkl. Nutzdaten) bleibt
+    /// unberuehrt, es wird nichts neu kodiert.
+    ///
+    /// # Fehler
+    /// - `InvalidData` wenn das Slice kuerzer als der Header ist
+    pub fn sequenz_schreiben(buf: &mut [u8], sequence: u32) -> io::Result<()> {
+        Self::feld_schreiben(buf, 4, sequence)
+    }
+
+    /// Ueberschreibt den Zeitstempel eines kodierten Pakets in-place
+    ///
+    /// # Fehler
+    /// - `InvalidData` wenn das Slice kuerzer als der Header ist
+    pub fn zeitstempel_schreiben(buf: &mut [u8], timestamp: u32) -> io::Result<()> {
+        Self::feld_schreiben(buf, 8, timestamp)
+    }
+
+    fn feld_schreiben(buf: &mut [u8], offset: usize, wert: u32) -> io::Result<()> {
+        if buf.len() < Self::SIZE {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "Header zu kurz: {} Bytes (erwartet {})",
+                    buf.len(),
+                    Self::SIZE
+                ),
+            ));
+        }
+        buf[offset..offset + 4].copy_from_slice(&wert.to_be_bytes());
+        Ok(())
+    }
 }
 
 // ---------------------------------------------------------------------------
@@ -274,6 +359,328 @@ impl VoicePacket {
     }
 }
 
+// ---------------------------------------------------------------------------
+// PingPaket
+// ---------------------------------------------------------------------------
+
+/// Groesse von Ping-Anfrage und -Antwort in Bytes
+pub const PING_GROESSE: usize = VoicePacketHeader::SIZE + 8;
+
+/// Ping zur Latenzmessung (siehe Modul-Dokumentation)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PingPaket {
+    /// Laufende Nummer der Probe
+    pub sequenz: u32,
+    /// Sendezeitpunkt des Clients (beliebige Einheit, kommt unveraendert zurueck)
+    pub client_zeit: u32,
+    /// Serverzeit in ms seit UNIX-Epoche (nur in Antworten, sonst 0)
+    pub server_zeit_ms: u64,
+    /// `true` fuer die Antwort des Servers
+    pub antwort: bool,
+}
+
+impl PingPaket {
+    /// Erstellt eine Ping-Anfrage
+    pub fn anfrage(sequenz: u32, client_zeit: u32) -> Self {
+        Self {
+            sequenz,
+            client_zeit,
+            server_zeit_ms: 0,
+            antwort: false,
+        }
+    }
+
+    /// Erstellt die Antwort auf diese Anfrage
+    pub fn beantworten(&self, server_zeit_ms: u64) -> Self {
+        Self {
+            server_zeit_ms,
+            antwort: true,
+            ..*self
+        }
+    }
+
+    /// Serialisiert den Ping
+    pub fn encode(&self) -> [u8; PING_GROESSE] {
+        let flags = if self.antwort {
+            VoiceFlags::PING_ANTWORT
+        } else {
+            0
+        };
+        let header =
+            VoicePacketHeader::new(PacketType::Ping, flags, self.sequenz, self.client_zeit, 0);
+        let mut buf = [0u8; PING_GROESSE];
+        buf[..VoicePacketHeader::SIZE].copy_from_slice(&header.encode());
+        buf[VoicePacketHeader::SIZE..].copy_from_slice(&self.server_zeit_ms.to_be_bytes());
+        buf
+    }
+
+    /// Deserialisiert einen Ping
+    ///
+    /// # Fehler
+    /// - `InvalidData` wenn die Laenge nicht genau [`PING_GROESSE`] ist
+    /// - `InvalidData` bei ungueltigem Header oder anderem PacketType
+    pub fn decode(buf: &[u8]) -> io::Result<Self> {
+        if buf.len() != PING_GROESSE {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "Ping mit falscher Laenge: {} Bytes (erwartet {})",
+                    buf.len(),
+                    PING_GROESSE
+                ),
+            ));
+        }
+        let header = VoicePacketHeader::decode(buf)?;
+        if header.packet_type != PacketType::Ping {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Kein Ping: {:?}", header.packet_type),
+            ));
+        }
+        let mut server_zeit = [0u8; 8];
+        server_zeit.copy_from_slice(&buf[VoicePacketHeader::SIZE..]);
+
+        Ok(Self {
+            sequenz: header.sequence,
+            client_zeit: header.timestamp,
+            server_zeit_ms: u64::from_be_bytes(server_zeit),
+            antwort: header.hat_flag(VoiceFlags::PING_ANTWORT),
+        })
+    }
+
+    /// Prueft anhand des Headers, ob ein Datagramm ein Ping ist
+    pub fn ist_ping(buf: &[u8]) -> bool {
+        buf.get(1) == Some(&(PacketType::Ping as u8))
+    }
+}
+
+// ---------------------------------------------------------------------------
+// HelloPaket
+// ---------------------------------------------------------------------------
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Laenge der Hello-Nonce in Bytes
+pub const HELLO_NONCE_LAENGE: usize = 32;
+
+/// Laenge des Hello-Tags (HMAC-SHA256) in Bytes
+pub const HELLO_TAG_LAENGE: usize = 32;
+
+/// Groesse von Hello und Bestaetigung in Bytes
+pub const HELLO_GROESSE: usize = VoicePacketHeader::SIZE + HELLO_TAG_LAENGE;
+
+/// Kontext-Praefix des Hello-HMAC
+const HELLO_KONTEXT: &[u8] = b"speakeasy-voice-hello";
+
+/// Anmeldung des UDP-Endpunkts (siehe Modul-Dokumentation)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HelloPaket {
+    /// SSRC aus `VoiceReady`
+    pub ssrc: u32,
+    /// Laufende Nummer des Versuchs
+    pub versuch: u32,
+    /// HMAC ueber SSRC und Versuch
+    pub tag: [u8; HELLO_TAG_LAENGE],
+    /// `true` fuer die Bestaetigung des Servers
+    pub antwort: bool,
+}
+
+impl HelloPaket {
+    /// Erstellt ein Hello fuer `ssrc`, authentifiziert mit der Nonce
+    pub fn neu(ssrc: u32, versuch: u32, nonce: &[u8]) -> Self {
+        Self {
+            ssrc,
+            versuch,
+            tag: hello_tag(nonce, ssrc, versuch),
+            antwort: false,
+        }
+    }
+
+    /// Erstellt die Bestaetigung dieses Hellos
+    pub fn bestaetigen(&self) -> Self {
+        Self {
+            antwort: true,
+            ..*self
+        }
+    }
+
+    /// Prueft den Tag gegen die Nonce (konstante Laufzeit)
+    pub fn pruefen(&self, nonce: &[u8]) -> bool {
+        let Ok(mut mac) = HmacSha256::new_from_slice(nonce) else {
+            return false;
+        };
+        mac.update(HELLO_KONTEXT);
+        mac.update(&self.ssrc.to_be_bytes());
+        mac.update(&self.versuch.to_be_bytes());
+        mac.verify_slice(&self.tag).is_ok()
+    }
+
+    /// Serialisiert das Hello
+    pub fn encode(&self) -> [u8; HELLO_GROESSE] {
+        let flags = if self.antwort {
+            VoiceFlags::HELLO_ANTWORT
+        } else {
+            0
+        };
+        let header = VoicePacketHeader::new(PacketType::Hello, flags, self.versuch, 0, self.ssrc);
+        let mut buf = [0u8; HELLO_GROESSE];
+        buf[..VoicePacketHeader::SIZE].copy_from_slice(&header.encode());
+        buf[VoicePacketHeader::SIZE..].copy_from_slice(&self.tag);
+        buf
+    }
+
+    /// Deserialisiert ein Hello
+    ///
+    /// # Fehler
+    /// - `InvalidData` wenn die Laenge nicht genau [`HELLO_GROESSE`] ist
+    /// - `InvalidData` bei ungueltigem Header oder anderem PacketType
+    pub fn decode(buf: &[u8]) -> io::Result<Self> {
+        if buf.len() != HELLO_GROESSE {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "Hello mit falscher Laenge: {} Bytes (erwartet {})",
+                    buf.len(),
+                    HELLO_GROESSE
+                ),
+            ));
+        }
+        let header = VoicePacketHeader::decode(buf)?;
+        if header.packet_type != PacketType::Hello {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Kein Hello: {:?}", header.packet_type),
+            ));
+        }
+        let mut tag = [0u8; HELLO_TAG_LAENGE];
+        tag.copy_from_slice(&buf[VoicePacketHeader::SIZE..]);
+
+        Ok(Self {
+            ssrc: header.ssrc,
+            versuch: header.sequence,
+            tag,
+            antwort: header.hat_flag(VoiceFlags::HELLO_ANTWORT),
+        })
+    }
+
+    /// Prueft anhand des Headers, ob ein Datagramm ein Hello ist
+    pub fn ist_hello(buf: &[u8]) -> bool {
+        buf.get(1) == Some(&(PacketType::Hello as u8))
+    }
+}
+
+/// HMAC-SHA256 ueber Kontext, SSRC und Versuch, geschluesselt mit der Nonce
+fn hello_tag(nonce: &[u8], ssrc: u32, versuch: u32) -> [u8; HELLO_TAG_LAENGE] {
+    // HMAC akzeptiert Schluessel jeder Laenge
+    let mut mac = HmacSha256::new_from_slice(nonce).expect("HMAC akzeptiert jede Schluessellaenge");
+    mac.update(HELLO_KONTEXT);
+    mac.update(&ssrc.to_be_bytes());
+    mac.update(&versuch.to_be_bytes());
+    mac.finalize().into_bytes().into()
+}
+
+/// Kodiert eine Hello-Nonce fuer `VoiceReadyResponse::hello_nonce`
+pub fn hello_nonce_kodieren(nonce: &[u8]) -> String {
+    nonce.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+/// Dekodiert eine Hello-Nonce aus `VoiceReadyResponse::hello_nonce`
+///
+/// Liefert `None` bei ungerader Laenge oder Zeichen ausserhalb von Hex.
+pub fn hello_nonce_dekodieren(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) || !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).ok())
+        .collect()
+}
+
+// ---------------------------------------------------------------------------
+// SequenzStatistik
+// ---------------------------------------------------------------------------
+
+/// Empfangsstatistik fuer den Paketstrom einer SSRC
+///
+/// Erwartete Pakete ergeben sich aus der Spanne der Sequenznummern, der
+/// Verlust aus der Differenz zu den tatsaechlich empfangenen Paketen.
+/// Ueberlaeufe der Sequenznummer werden beruecksichtigt; verspaetete
+/// Pakete zaehlen als empfangen und verringern den Verlust nachtraeglich.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SequenzStatistik {
+    /// Erste empfangene Sequenznummer
+    basis: Option<u32>,
+    /// Hoechste empfangene Sequenznummer (ohne Ueberlauf)
+    hoechste: u32,
+    /// Hoechste Sequenznummer relativ zur Basis (mit Ueberlaeufen)
+    spanne: u64,
+    /// Empfangene Pakete
+    empfangen: u64,
+}
+
+impl SequenzStatistik {
+    /// Verbucht ein empfangenes Paket
+    pub fn paket(&mut self, sequenz: u32) {
+        self.empfangen += 1;
+        if self.basis.is_none() {
+            self.basis = Some(sequenz);
+            self.hoechste = sequenz;
+            return;
+        }
+        let abstand = sequenz.wrapping_sub(self.hoechste) as i32;
+        if abstand > 0 {
+            self.spanne += abstand as u64;
+            self.hoechste = sequenz;
+        }
+    }
+
+    /// Anzahl empfangener Pakete
+    pub fn empfangen(&self) -> u64 {
+        self.empfangen
+    }
+
+    /// Anzahl erwarteter Pakete (Spanne der Sequenznummern)
+    pub fn erwartet(&self) -> u64 {
+        if self.basis.is_some() {
+            self.spanne + 1
+        } else {
+            0
+        }
+    }
+
+    /// Anzahl verlorener Pakete (Duplikate werden nicht negativ gezaehlt)
+    pub fn verloren(&self) -> u64 {
+        self.erwartet().saturating_sub(self.empfangen)
+    }
+
+    /// Hoechste empfangene Sequenznummer
+    pub fn hoechste_sequenz(&self) -> Option<u32> {
+        self.basis.map(|_| self.hoechste)
+    }
+
+    /// Gesamte Verlust-Rate (0.0–1.0)
+    pub fn verlust_rate(&self) -> f64 {
+        verlust_rate(self.empfangen, self.erwartet())
+    }
+
+    /// Verlust-Rate seit einem frueheren Stand derselben Statistik
+    pub fn verlust_rate_seit(&self, vorher: &SequenzStatistik) -> f64 {
+        verlust_rate(
+            self.empfangen.saturating_sub(vorher.empfangen),
+            self.erwartet().saturating_sub(vorher.erwartet()),
+        )
+    }
+}
+
+/// Verlust-Rate aus empfangenen und erwarteten Paketen (0.0–1.0)
+pub fn verlust_rate(empfangen: u64, erwartet: u64) -> f64 {
+    if erwartet == 0 {
+        return 0.0;
+    }
+    erwartet.saturating_sub(empfangen) as f64 / erwartet as f64
+}
+
 // ---------------------------------------------------------------------------
 // Veralteter VoicePaket-Typ (Rueckwaertskompatibilitaet Phase 1)
 // ---------------------------------------------------------------------------
@@ -291,6 +698,32 @@ pub enum AudioCodec {
     Pcmu,
 }
 
+impl AudioCodec {
+    /// Name in der Codec-Aushandlung (`VoiceInit`/`VoiceReady`)
+    pub fn name(&self) -> &'static str {
+        match self {
+            AudioCodec::Opus => "opus",
+            AudioCodec::Pcmu => "pcmu",
+        }
+    }
+
+    /// Codec zu einem ausgehandelten Namen (Gross-/Kleinschreibung egal)
+    pub fn aus_name(name: &str) -> Option<Self> {
+        [AudioCodec::Opus, AudioCodec::Pcmu]
+            .into_iter()
+            .find(|codec| codec.name().eq_ignore_ascii_case(name))
+    }
+
+    /// Codec der Nutzdaten eines Voice-Pakets
+    pub fn von_header(header: &VoicePacketHeader) -> Self {
+        if header.hat_flag(VoiceFlags::PCMU) {
+            AudioCodec::Pcmu
+        } else {
+            AudioCodec::Opus
+        }
+    }
+}
+
 /// High-Level Voice-Paket fuer Signaling (serde-kompatibel)
 ///
 /// Wird intern verwendet um Metadaten ueber Pakete auszutauschen.
@@ -391,6 +824,22 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn header_felder_in_place_schreiben() {
+        let paket = VoicePacket::neu_audio(7, 6720, 0xABCD, vec![1, 2, 3]);
+        let mut bytes = paket.encode();
+        VoicePacketHeader::sequenz_schreiben(&mut bytes, 5).unwrap();
+        VoicePacketHeader::zeitstempel_schreiben(&mut bytes, 4800).unwrap();
+
+        let umgeschrieben = VoicePacket::decode(&bytes).unwrap();
+        assert_eq!(umgeschrieben.header.sequence, 5);
+        assert_eq!(umgeschrieben.header.timestamp, 4800);
+        assert_eq!(umgeschrieben.header.ssrc, 0xABCD);
+        assert_eq!(umgeschrieben.payload, paket.payload);
+
+        assert!(VoicePacketHeader::sequenz_schreiben(&mut [0u8; 8], 1).is_err());
+    }
+
     #[test]
     fn header_decode_zu_kurz() {
         let bytes = [0u8; 8]; // Nur 8 Bytes statt 16
@@ -406,6 +855,79 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn ping_round_trip_und_strikte_laenge() {
+        let anfrage = PingPaket::anfrage(7, 123_456);
+        let bytes = anfrage.encode();
+        assert_eq!(bytes.len(), PING_GROESSE);
+        assert!(PingPaket::ist_ping(&bytes));
+        assert_eq!(PingPaket::decode(&bytes).unwrap(), anfrage);
+
+        let antwort = anfrage.beantworten(1_700_000_000_000);
+        let dekodiert = PingPaket::decode(&antwort.encode()).unwrap();
+        assert!(dekodiert.antwort);
+        assert_eq!(dekodiert.sequenz, 7);
+        assert_eq!(dekodiert.client_zeit, 123_456);
+        assert_eq!(dekodiert.server_zeit_ms, 1_700_000_000_000);
+
+        // Zu kurz, zu lang oder kein Ping
+        assert!(PingPaket::decode(&bytes[..PING_GROESSE - 1]).is_err());
+        let mut zu_lang = bytes.to_vec();
+        zu_lang.push(0);
+        assert!(PingPaket::decode(&zu_lang).is_err());
+        let audio = VoicePacket::neu_audio(1, 960, 1, vec![0; 8]).encode();
+        assert!(!PingPaket::ist_ping(&audio));
+        assert!(PingPaket::decode(&audio).is_err());
+    }
+
+    #[test]
+    fn hello_round_trip_und_hmac_pruefung() {
+        let nonce = [7u8; HELLO_NONCE_LAENGE];
+        let hello = HelloPaket::neu(0xCAFE, 3, &nonce);
+        let bytes = hello.encode();
+        assert_eq!(bytes.len(), HELLO_GROESSE);
+        assert!(HelloPaket::ist_hello(&bytes));
+        assert!(!PingPaket::ist_ping(&bytes));
+
+        let dekodiert = HelloPaket::decode(&bytes).unwrap();
+        assert_eq!(dekodiert, hello);
+        assert!(dekodiert.pruefen(&nonce));
+        assert!(
+            HelloPaket::decode(&hello.bestaetigen().encode())
+                .unwrap()
+                .antwort
+        );
+
+        // Falsche Nonce, fremde SSRC, anderer Versuch oder veraenderter Tag
+        // fallen durch
+        assert!(!dekodiert.pruefen(&[8u8; HELLO_NONCE_LAENGE]));
+        let fremd = HelloPaket {
+            ssrc: 0xBEEF,
+            ..dekodiert
+        };
+        assert!(!fremd.pruefen(&nonce));
+        let spaeter = HelloPaket {
+            versuch: 4,
+            ..dekodiert
+        };
+        assert!(!spaeter.pruefen(&nonce));
+        let mut manipuliert = bytes;
+        manipuliert[HELLO_GROESSE - 1] ^= 1;
+        assert!(!HelloPaket::decode(&manipuliert).unwrap().pruefen(&nonce));
+        assert!(HelloPaket::decode(&bytes[..HELLO_GROESSE - 1]).is_err());
+    }
+
+    #[test]
+    fn hello_nonce_hex_round_trip() {
+        let nonce: Vec<u8> = (0..HELLO_NONCE_LAENGE as u8).collect();
+        let hex = hello_nonce_kodieren(&nonce);
+        assert_eq!(hex.len(), HELLO_NONCE_LAENGE * 2);
+        assert_eq!(hello_nonce_dekodieren(&hex), Some(nonce));
+        assert_eq!(hello_nonce_dekodieren("abc"), None);
+        assert_eq!(hello_nonce_dekodieren("zz"), None);
+        assert_eq!(hello_nonce_dekodieren("+f"), None);
+    }
+
     #[test]
     fn voice_packet_encode_decode_round_trip() {
         let payload = vec![0xAB; 120];
@@ -455,6 +977,72 @@ mod tests {
         assert!(!header.hat_flag(VoiceFlags::SPEAKING_START));
     }
 
+    #[test]
+    fn audio_codec_namen_und_header() {
+        assert_eq!(AudioCodec::aus_name("PCMU"), Some(AudioCodec::Pcmu));
+        assert_eq!(AudioCodec::aus_name("opus"), Some(AudioCodec::Opus));
+        assert_eq!(AudioCodec::aus_name("speex"), None);
+
+        let header = VoicePacketHeader::new(PacketType::Audio, VoiceFlags::PCMU, 0, 0, 0);
+        assert_eq!(AudioCodec::von_header(&header), AudioCodec::Pcmu);
+        let header = VoicePacketHeader::new(PacketType::Audio, 0, 0, 0, 0);
+        assert_eq!(AudioCodec::von_header(&header), AudioCodec::Opus);
+    }
+
+    // --- SequenzStatistik ---
+
+    #[test]
+    fn sequenz_statistik_ohne_verlust() {
+        let mut stat = SequenzStatistik::default();
+        assert_eq!(stat.hoechste_sequenz(), None);
+        for seq in 10..20 {
+            stat.paket(seq);
+        }
+        assert_eq!(stat.empfangen(), 10);
+        assert_eq!(stat.erwartet(), 10);
+        assert_eq!(stat.verloren(), 0);
+        assert_eq!(stat.hoechste_sequenz(), Some(19));
+    }
+
+    #[test]
+    fn sequenz_statistik_luecken_und_verspaetung() {
+        let mut stat = SequenzStatistik::default();
+        for seq in [0, 1, 3, 4, 7] {
+            stat.paket(seq);
+        }
+        assert_eq!(stat.erwartet(), 8);
+        assert_eq!(stat.verloren(), 3);
+
+        // Verspaetetes Paket fuellt eine Luecke
+        stat.paket(2);
+        assert_eq!(stat.verloren(), 2);
+        assert!((stat.verlust_rate() - 0.25).abs() < 1e-9);
+    }
+
+    #[test]
+    fn sequenz_statistik_ueberlauf() {
+        let mut stat = SequenzStatistik::default();
+        for seq in [u32::MAX - 1, u32::MAX, 1] {
+            stat.paket(seq);
+        }
+        assert_eq!(stat.erwartet(), 4);
+        assert_eq!(stat.verloren(), 1);
+    }
+
+    #[test]
+    fn sequenz_statistik_verlust_seit() {
+        let mut stat = SequenzStatistik::default();
+        for seq in 0..10 {
+            stat.paket(seq);
+        }
+        let vorher = stat;
+        for seq in (10..20).step_by(2) {
+            stat.paket(seq);
+        }
+        // 10..=18: 9 erwartet, 5 empfangen
+        assert!((stat.verlust_rate_seit(&vorher) - 4.0 / 9.0).abs() < 1e-9);
+    }
+
     // --- Rueckwaertskompatibilitaet VoicePaket ---
 
     #[test]
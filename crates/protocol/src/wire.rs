@@ -11,9 +11,12 @@
 //! ```
 //!
 //! Die Laenge gibt die Anzahl der Payload-Bytes an (ohne die 4 Laengen-Bytes).
-//! Maximale Frame-Groesse ist konfigurierbar (Standard: 1 MB).
+//! Maximale Frame-Groesse ist konfigurierbar (Standard: 1 MB). Zu grosse
+//! Frames ergeben einen `InvalidData`-Fehler mit [`FrameZuGross`] als
+//! Ursache, damit die Gegenstelle vor dem Trennen antworten kann.
 
 use bytes::{Buf, BufMut, BytesMut};
+use std::fmt;
 use std::io;
 use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
 use tokio_util::codec::{Decoder, Encoder};
@@ -30,6 +33,51 @@ pub const DEFAULT_MAX_FRAME_SIZE: usize = 1024 * 1024;
 /// Groesse des Laengen-Felds in Bytes
 pub const LENGTH_FIELD_SIZE: usize = 4;
 
+// ---------------------------------------------------------------------------
+// Fehler
+// ---------------------------------------------------------------------------
+
+/// Ein Frame ueberschreitet die maximale Frame-Groesse
+///
+/// Steckt als Ursache in einem `io::Error` der Art `InvalidData`;
+/// [`FrameZuGross::aus_io`] holt ihn wieder heraus.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FrameZuGross {
+    /// Laenge des Frames in Bytes
+    pub laenge: usize,
+    /// Erlaubtes Maximum in Bytes
+    pub maximum: usize,
+}
+
+impl FrameZuGross {
+    /// Gibt den Frame-Fehler zurueck, falls `fehler` einer ist
+    pub fn aus_io(fehler: &io::Error) -> Option<&Self> {
+        fehler.get_ref()?.downcast_ref()
+    }
+
+    fn pruefen(laenge: usize, maximum: usize) -> io::Result<()> {
+        if laenge > maximum {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                Self { laenge, maximum },
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for FrameZuGross {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Frame zu gross: {} Bytes (Maximum: {} Bytes)",
+            self.laenge, self.maximum
+        )
+    }
+}
+
+impl std::error::Error for FrameZuGross {}
+
 // ---------------------------------------------------------------------------
 // FrameCodec
 // ---------------------------------------------------------------------------
@@ -52,19 +100,34 @@ pub const LENGTH_FIELD_SIZE: usize = 4;
 pub struct FrameCodec {
     /// Maximale erlaubte Frame-Groesse in Bytes
     max_frame_size: usize,
+    /// Maximale Groesse gesendeter Frames in Bytes
+    max_sende_size: usize,
 }
 
 impl FrameCodec {
     /// Erstellt einen neuen `FrameCodec` mit Standard-Limits
     pub fn new() -> Self {
-        Self {
-            max_frame_size: DEFAULT_MAX_FRAME_SIZE,
-        }
+        Self::with_max_size(DEFAULT_MAX_FRAME_SIZE)
     }
 
     /// Erstellt einen `FrameCodec` mit benutzerdefinierter maximaler Frame-Groesse
     pub fn with_max_size(max_frame_size: usize) -> Self {
-        Self { max_frame_size }
+        Self {
+            max_frame_size,
+            max_sende_size: max_frame_size,
+        }
+    }
+
+    /// Begrenzt nur empfangene Frames, gesendete duerfen bis zum Standard-Limit
+    /// gross sein
+    ///
+    /// Fuer Server, die Clients strenger begrenzen: Fehlerantworten, Listen und
+    /// Verlaeufe bleiben so zustellbar.
+    pub fn mit_empfangslimit(max_frame_size: usize) -> Self {
+        Self {
+            max_frame_size,
+            max_sende_size: DEFAULT_MAX_FRAME_SIZE,
+        }
     }
 
     /// Gibt die konfigurierte maximale Frame-Groesse zurueck
@@ -97,15 +160,7 @@ impl Decoder for FrameCodec {
         let length = u32::from_be_bytes([src[0], src[1], src[2], src[3]]) as usize;
 
         // Maximale Frame-Groesse pruefen
-        if length > self.max_frame_size {
-            return Err(io::Error::new(
-                io::ErrorKind::InvalidData,
-                format!(
-                    "Frame zu gross: {} Bytes (Maximum: {} Bytes)",
-                    length, self.max_frame_size
-                ),
-            ));
-        }
+        FrameZuGross::pruefen(length, self.max_frame_size)?;
 
         // Pruefen ob der vollstaendige Frame bereits im Buffer ist
         let total_size = LENGTH_FIELD_SIZE + length;
@@ -150,16 +205,7 @@ impl Encoder<ControlMessage> for FrameCodec {
         })?;
 
         // Groesse pruefen
-        if json.len() > self.max_frame_size {
-            return Err(io::Error::new(
-                io::ErrorKind::InvalidData,
-                format!(
-                    "Nachricht zu gross: {} Bytes (Maximum: {} Bytes)",
-                    json.len(),
-                    self.max_frame_size
-                ),
-            ));
-        }
+        FrameZuGross::pruefen(json.len(), self.max_sende_size)?;
 
         // Laengen-Feld + Payload schreiben
         dst.reserve(LENGTH_FIELD_SIZE + json.len());
@@ -189,15 +235,7 @@ where
     let length = u32::from_be_bytes(len_buf) as usize;
 
     // Groesse pruefen
-    if length > max_frame_size {
-        return Err(io::Error::new(
-            io::ErrorKind::InvalidData,
-            format!(
-                "Frame zu gross: {} Bytes (Maximum: {} Bytes)",
-                length, max_frame_size
-            ),
-        ));
-    }
+    FrameZuGross::pruefen(length, max_frame_size)?;
 
     // Payload lesen
     let mut payload = vec![0u8; length];
@@ -234,16 +272,7 @@ where
     })?;
 
     // Groesse pruefen
-    if json.len() > max_frame_size {
-        return Err(io::Error::new(
-            io::ErrorKind::InvalidData,
-            format!(
-                "Nachricht zu gross: {} Bytes (Maximum: {} Bytes)",
-                json.len(),
-                max_frame_size
-            ),
-        ));
-    }
+    FrameZuGross::pruefen(json.len(), max_frame_size)?;
 
     // Laengen-Feld + Payload schreiben
     let len_bytes = (json.len() as u32).to_be_bytes();
@@ -324,8 +353,15 @@ mod tests {
         buf.put_u32(200); // 200 Bytes Payload
         buf.put_slice(&[b'x'; 200]);
 
-        let result = codec.decode(&mut buf);
-        assert!(result.is_err());
+        let fehler = codec.decode(&mut buf).unwrap_err();
+        assert_eq!(fehler.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(
+            FrameZuGross::aus_io(&fehler),
+            Some(&FrameZuGross {
+                laenge: 200,
+                maximum: 100
+            })
+        );
     }
 
     #[test]
@@ -339,6 +375,27 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn andere_lesefehler_sind_kein_frame_fehler() {
+        let mut codec = FrameCodec::new();
+        let mut buf = BytesMut::new();
+        buf.put_u32(4);
+        buf.put_slice(b"kein");
+        let fehler = codec.decode(&mut buf).unwrap_err();
+        assert!(FrameZuGross::aus_io(&fehler).is_none());
+    }
+
+    #[test]
+    fn empfangslimit_gilt_nicht_fuer_gesendete_frames() {
+        let mut codec = FrameCodec::mit_empfangslimit(10);
+        assert_eq!(codec.max_frame_size(), 10);
+
+        let mut buf = BytesMut::new();
+        codec.encode(test_ping_nachricht(1), &mut buf).unwrap();
+        let fehler = codec.decode(&mut buf).unwrap_err();
+        assert!(FrameZuGross::aus_io(&fehler).is_some());
+    }
+
     #[test]
     fn frame_codec_mehrere_nachrichten_im_buffer() {
         let mut codec = FrameCodec::new();
@@ -399,8 +456,13 @@ mod tests {
         buffer.extend_from_slice(&(2u32 * 1024 * 1024).to_be_bytes());
 
         let mut cursor = io::Cursor::new(buffer);
-        let result = read_frame(&mut cursor, DEFAULT_MAX_FRAME_SIZE).await;
-        assert!(result.is_err());
+        let fehler = read_frame(&mut cursor, DEFAULT_MAX_FRAME_SIZE)
+            .await
+            .unwrap_err();
+        assert_eq!(
+            FrameZuGross::aus_io(&fehler).map(|f| f.laenge),
+            Some(2 * 1024 * 1024)
+        );
     }
 
     #[tokio::test]
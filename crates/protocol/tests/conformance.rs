@@ -0,0 +1,140 @@
+//! Prueft die Konformitaets-Testvektoren in `crates/protocol/conformance/`
+//!
+//! Schlaegt ein Test fehl, hat sich die Kodierung geaendert. Das ist nur
+//! zusammen mit einer neuen `ProtokollVersion::AKTUELL` zulaessig; danach
+//! die Vektoren mit `cargo run -p speakeasy-protocol --bin konformitaet`
+//! neu erzeugen.
+
+use std::collections::HashSet;
+use std::path::PathBuf;
+
+use bytes::BytesMut;
+use speakeasy_protocol::conformance::{
+    fingerabdruck, hex_dekodieren, protokoll_version, vektor_dateien, ControlVektor, Erwartung,
+    Manifest, VoiceVektor, WireVektoren, MANIFEST_DATEI, VEKTOR_VERZEICHNIS,
+};
+use speakeasy_protocol::control::ControlMessage;
+use speakeasy_protocol::voice::VoicePacket;
+use speakeasy_protocol::wire::FrameCodec;
+use tokio_util::codec::Decoder;
+
+fn pfad(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join(VEKTOR_VERZEICHNIS)
+        .join(name)
+}
+
+fn lesen(name: &str) -> String {
+    std::fs::read_to_string(pfad(name)).unwrap_or_else(|e| {
+        panic!(
+            "{name} fehlt ({e}); mit `cargo run -p speakeasy-protocol --bin konformitaet` erzeugen"
+        )
+    })
+}
+
+#[test]
+fn vektoren_entsprechen_aktueller_kodierung() {
+    for (name, erwartet) in vektor_dateien() {
+        assert!(
+            lesen(name) == erwartet,
+            "{name} weicht von der aktuellen Kodierung ab. Protokollversion erhoehen \
+             und Vektoren neu erzeugen."
+        );
+    }
+}
+
+#[test]
+fn manifest_passt_zur_protokollversion() {
+    let manifest: Manifest = serde_json::from_str(&lesen(MANIFEST_DATEI)).unwrap();
+    let version = protokoll_version();
+    let eintrag = manifest
+        .eintrag(&version)
+        .unwrap_or_else(|| panic!("Kein Manifest-Eintrag fuer Protokollversion {version}"));
+
+    // Fingerabdruck der Dateien auf der Platte, nicht der generierten:
+    // wer die Dateien ohne Versionssprung neu erzeugt, faellt hier auf.
+    let auf_platte: Vec<(&str, String)> = vektor_dateien()
+        .into_iter()
+        .map(|(name, _)| (name, lesen(name)))
+        .collect();
+    assert_eq!(
+        eintrag.fingerabdruck,
+        fingerabdruck(&auf_platte),
+        "Vektoren fuer Protokollversion {version} wurden ohne Versionssprung geaendert"
+    );
+
+    let versionen: HashSet<_> = manifest
+        .versionen
+        .iter()
+        .map(|e| &e.protokoll_version)
+        .collect();
+    assert_eq!(
+        versionen.len(),
+        manifest.versionen.len(),
+        "Doppelte Manifest-Eintraege"
+    );
+}
+
+#[test]
+fn control_vektoren_round_trip() {
+    let vektoren: Vec<ControlVektor> = serde_json::from_str(&lesen("control.json")).unwrap();
+    let namen: HashSet<_> = vektoren.iter().map(|v| v.name.as_str()).collect();
+    assert_eq!(
+        namen.len(),
+        vektoren.len(),
+        "Variantennamen muessen eindeutig sein"
+    );
+
+    for vektor in &vektoren {
+        let msg = ControlMessage::from_json(&vektor.json)
+            .unwrap_or_else(|e| panic!("{}: nicht dekodierbar: {e}", vektor.name));
+        assert_eq!(msg.to_json().unwrap(), vektor.json, "{}", vektor.name);
+    }
+}
+
+#[test]
+fn voice_vektoren_dekodieren() {
+    let vektoren: Vec<VoiceVektor> = serde_json::from_str(&lesen("voice.json")).unwrap();
+    for vektor in &vektoren {
+        let bytes = hex_dekodieren(&vektor.hex).expect("gueltiges Hex");
+        match (vektor.erwartung, VoicePacket::decode(&bytes)) {
+            (Erwartung::Ok, Ok(paket)) => {
+                assert_eq!(
+                    paket.encode(),
+                    bytes,
+                    "{}: Re-Encoding weicht ab",
+                    vektor.name
+                )
+            }
+            (Erwartung::Fehler, Err(_)) => {}
+            (erwartung, ergebnis) => {
+                panic!(
+                    "{}: erwartet {erwartung:?}, erhalten {ergebnis:?}",
+                    vektor.name
+                )
+            }
+        }
+    }
+}
+
+#[test]
+fn wire_vektoren_dekodieren() {
+    let datei: WireVektoren = serde_json::from_str(&lesen("wire.json")).unwrap();
+    for vektor in &datei.vektoren {
+        let mut codec = FrameCodec::with_max_size(datei.max_frame_size);
+        let mut buf = BytesMut::from(hex_dekodieren(&vektor.hex).unwrap().as_slice());
+
+        let mut nachrichten = 0;
+        let ergebnis = loop {
+            match codec.decode(&mut buf) {
+                Ok(Some(_)) => nachrichten += 1,
+                Ok(None) if buf.is_empty() => break Erwartung::Ok,
+                Ok(None) => break Erwartung::Unvollstaendig,
+                Err(_) => break Erwartung::Fehler,
+            }
+        };
+
+        assert_eq!(ergebnis, vektor.erwartung, "{}", vektor.name);
+        assert_eq!(nachrichten, vektor.nachrichten, "{}", vektor.name);
+    }
+}
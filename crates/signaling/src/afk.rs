@@ -0,0 +1,356 @@
+//! AFK-Erkennung – verschiebt inaktive Clients in den AFK-Kanal
+//!
+//! Die letzte Aktivitaet pro Client liefert der `AktivitaetsTracker`
+//! (Sprachpakete mit Speaking-Status, Chat, `ClientActivity`-Pings).
+//! Ein Hintergrund-Task prueft periodisch alle Clients in einem Kanal und
+//! verschiebt diejenigen, deren Inaktivitaet die Schwelle der Richtlinie
+//! erreicht – ueber denselben Weg wie ein manueller `ClientMove`, mit
+//! dem Grund `"idle"`. Zurueckverschieben ist Sache des Benutzers.
+//!
+//! Clients mit explizit gewaehrtem `b_afk_exempt` werden uebersprungen.
+
+use speakeasy_core::types::{ChannelId, UserId};
+use speakeasy_db::{
+    models::{BerechtigungsWert, TriState},
+    repository::UserRepository,
+    BanRepository, ChannelRepository, ChatMessageRepository, PermissionRepository,
+    ServerGroupRepository,
+};
+use speakeasy_voice::AktivitaetsTracker;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use tokio::time::Instant;
+
+use crate::handlers::{client_handler, voice_handler};
+use crate::presence::PresenceManager;
+use crate::server_state::SignalingState;
+
+/// Berechtigung, die von der automatischen AFK-Verschiebung ausnimmt
+pub const AFK_BEFREIUNG: &str = "b_afk_exempt";
+/// Grund, der beim automatischen Verschieben mitgesendet wird
+pub const AFK_GRUND: &str = "idle";
+/// Intervall der Hintergrund-Pruefung
+pub const PRUEF_INTERVALL: Duration = Duration::from_secs(10);
+
+// ---------------------------------------------------------------------------
+// Richtlinie
+// ---------------------------------------------------------------------------
+
+/// Server-weite AFK-Richtlinie
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AfkRichtlinie {
+    /// Inaktivitaet bis zur Verschiebung (`Duration::ZERO` = deaktiviert)
+    pub schwelle: Duration,
+    /// Ziel-Kanal fuer inaktive Clients
+    pub afk_kanal: Option<ChannelId>,
+}
+
+impl AfkRichtlinie {
+    /// Gibt den AFK-Kanal zurueck, sofern die Richtlinie aktiv ist
+    pub fn aktiver_kanal(&self) -> Option<ChannelId> {
+        self.afk_kanal.filter(|_| !self.schwelle.is_zero())
+    }
+}
+
+/// Zur Laufzeit aenderbare AFK-Richtlinie (Clone teilt den Zustand)
+#[derive(Clone, Default)]
+pub struct AfkWaechter {
+    richtlinie: Arc<RwLock<AfkRichtlinie>>,
+}
+
+impl AfkWaechter {
+    /// Erstellt einen Waechter mit Startrichtlinie
+    pub fn neu(richtlinie: AfkRichtlinie) -> Self {
+        Self {
+            richtlinie: Arc::new(RwLock::new(richtlinie)),
+        }
+    }
+
+    /// Gibt die aktuelle Richtlinie zurueck
+    pub fn richtlinie(&self) -> AfkRichtlinie {
+        *self.richtlinie.read().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Aendert Schwelle und/oder AFK-Kanal (`None` = unveraendert)
+    pub fn richtlinie_aendern(&self, schwelle: Option<Duration>, afk_kanal: Option<ChannelId>) {
+        let mut r = self.richtlinie.write().unwrap_or_else(|e| e.into_inner());
+        if let Some(schwelle) = schwelle {
+            r.schwelle = schwelle;
+        }
+        if afk_kanal.is_some() {
+            r.afk_kanal = afk_kanal;
+        }
+        tracing::info!(
+            schwelle_sek = r.schwelle.as_secs(),
+            afk_kanal = ?r.afk_kanal,
+            "AFK-Richtlinie geaendert"
+        );
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Pruefung
+// ---------------------------------------------------------------------------
+
+/// Ermittelt alle Clients in einem Kanal, die die Schwelle erreicht haben
+///
+/// Clients ohne Aktivitaetseintrag und Clients im AFK-Kanal selbst werden
+/// nicht beruecksichtigt.
+pub fn kandidaten(
+    presence: &PresenceManager,
+    aktivitaet: &AktivitaetsTracker,
+    richtlinie: &AfkRichtlinie,
+    jetzt: Instant,
+) -> Vec<UserId> {
+    let Some(afk_kanal) = richtlinie.aktiver_kanal() else {
+        return vec![];
+    };
+    presence
+        .alle_clients()
+        .into_iter()
+        .filter(|p| p.channel_id.is_some_and(|c| c != afk_kanal))
+        .filter(|p| {
+            aktivitaet
+                .inaktiv_seit(&p.user_id, jetzt)
+                .is_some_and(|d| d >= richtlinie.schwelle)
+        })
+        .map(|p| p.user_id)
+        .collect()
+}
+
+/// Verschiebt alle inaktiven Clients in den AFK-Kanal
+///
+/// Gibt die verschobenen User-IDs zurueck.
+pub async fn idle_clients_verschieben<U, P, B>(
+    state: &Arc<SignalingState<U, P, B>>,
+    jetzt: Instant,
+) -> Vec<UserId>
+where
+    U: UserRepository + ServerGroupRepository + ChannelRepository + ChatMessageRepository + 'static,
+    P: PermissionRepository + 'static,
+    B: BanRepository + 'static,
+{
+    let richtlinie = state.afk.richtlinie();
+    let Some(afk_kanal) = richtlinie.aktiver_kanal() else {
+        return vec![];
+    };
+
+    // Clients ohne Eintrag (z.B. nach Server-Neustart) ab jetzt zaehlen
+    for p in state.presence.alle_clients() {
+        state.aktivitaet.erfassen_falls_neu(p.user_id, jetzt);
+    }
+
+    let mut verschoben = Vec::new();
+    for user_id in kandidaten(&state.presence, &state.aktivitaet, &richtlinie, jetzt) {
+        if ist_befreit(state, user_id).await {
+            continue;
+        }
+        client_handler::client_verschieben(state, user_id, afk_kanal, Some(AFK_GRUND.into()));
+        voice_handler::sendemodus_neu_bewerten(state, user_id, afk_kanal).await;
+        verschoben.push(user_id);
+    }
+
+    if !verschoben.is_empty() {
+        tracing::info!(
+            anzahl = verschoben.len(),
+            afk_kanal = %afk_kanal,
+            "Inaktive Clients in den AFK-Kanal verschoben"
+        );
+    }
+    verschoben
+}
+
+/// Hintergrund-Task: prueft periodisch bis zum Shutdown
+pub async fn afk_loop<U, P, B>(
+    state: Arc<SignalingState<U, P, B>>,
+    mut shutdown_rx: tokio::sync::watch::Receiver<bool>,
+) where
+    U: UserRepository + ServerGroupRepository + ChannelRepository + ChatMessageRepository + 'static,
+    P: PermissionRepository + 'static,
+    B: BanRepository + 'static,
+{
+    let mut intervall = tokio::time::interval(PRUEF_INTERVALL);
+    intervall.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+    loop {
+        tokio::select! {
+            _ = intervall.tick() => {
+                idle_clients_verschieben(&state, Instant::now()).await;
+            }
+            Ok(()) = shutdown_rx.changed() => {
+                if *shutdown_rx.borrow() {
+                    break;
+                }
+            }
+        }
+    }
+    tracing::debug!("AFK-Pruefung beendet");
+}
+
+/// Prueft ob ein Client explizit von der AFK-Verschiebung ausgenommen ist
+///
+/// Anders als bei `berechtigung_pruefen` zaehlt hier nur ein ausdrueckliches
+/// Grant – ohne Regel wird verschoben. Bei Fehlern wird nicht verschoben.
+async fn ist_befreit<U, P, B>(state: &Arc<SignalingState<U, P, B>>, user_id: UserId) -> bool
+where
+    U: UserRepository + ServerGroupRepository + ChannelRepository + ChatMessageRepository + 'static,
+    P: PermissionRepository + 'static,
+    B: BanRepository + 'static,
+{
+    let kanal = state
+        .presence
+        .channel_von_client(&user_id)
+        .unwrap_or_else(|| ChannelId(uuid::Uuid::nil()));
+    match state
+        .permission_service
+        .alle_berechtigungen_holen(user_id.inner(), kanal.inner())
+        .await
+    {
+        Ok(perms) => matches!(
+            perms.get(AFK_BEFREIUNG),
+            Some(BerechtigungsWert::TriState(TriState::Grant))
+        ),
+        Err(e) => {
+            tracing::warn!(user_id = %user_id, fehler = %e, "AFK-Befreiung nicht pruefbar");
+            true
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_hilfen::{neuer_client, TestAufbau, TestState};
+    use speakeasy_db::models::{BerechtigungsZiel, NeuerKanal};
+    use speakeasy_protocol::control::ControlPayload;
+
+    const SCHWELLE: Duration = Duration::from_secs(300);
+
+    async fn state(afk_kanal: ChannelId) -> TestState {
+        TestAufbau::default()
+            .afk_kanal(afk_kanal, SCHWELLE)
+            .bauen()
+            .await
+    }
+
+    fn client_anmelden(state: &TestState, kanal: ChannelId) -> UserId {
+        let user_id = neuer_client(state, Some(kanal));
+        state.aktivitaet.melden(user_id);
+        user_id
+    }
+
+    #[tokio::test]
+    async fn verschiebt_genau_an_der_schwelle() {
+        let afk = ChannelId::new();
+        let lobby = ChannelId::new();
+        let state = state(afk).await;
+        let user = client_anmelden(&state, lobby);
+        let mut rx = state.broadcaster.client_registrieren(user);
+        let start = state.aktivitaet.letzte_aktivitaet(&user).unwrap();
+
+        let knapp = start + SCHWELLE - Duration::from_secs(1);
+        assert!(idle_clients_verschieben(&state, knapp).await.is_empty());
+
+        assert_eq!(
+            idle_clients_verschieben(&state, start + SCHWELLE).await,
+            vec![user]
+        );
+        assert_eq!(state.presence.channel_von_client(&user), Some(afk));
+
+        // Bereits im AFK-Kanal: kein zweites Verschieben
+        assert!(idle_clients_verschieben(&state, start + SCHWELLE * 2)
+            .await
+            .is_empty());
+
+        let mut grund = None;
+        while let Ok(nachricht) = rx.try_recv() {
+            if let ControlPayload::ClientMoved(ev) = nachricht.payload {
+                assert_eq!(ev.from_channel_id, Some(lobby));
+                assert_eq!(ev.to_channel_id, afk);
+                grund = ev.reason;
+            }
+        }
+        assert_eq!(grund.as_deref(), Some(AFK_GRUND));
+    }
+
+    #[tokio::test]
+    async fn befreite_clients_werden_uebersprungen() {
+        let afk = ChannelId::new();
+        let state = state(afk).await;
+        // Kanal-Berechtigungen benoetigen einen existierenden Kanal
+        let lobby = ChannelRepository::create(
+            state.db.as_ref(),
+            NeuerKanal {
+                name: "Lobby",
+                ..Default::default()
+            },
+        )
+        .await
+        .map(|k| ChannelId(k.id))
+        .unwrap();
+        let normal = client_anmelden(&state, lobby);
+        let befreit = client_anmelden(&state, lobby);
+        state
+            .db
+            .set_permission(
+                &BerechtigungsZiel::Benutzer(befreit.inner()),
+                AFK_BEFREIUNG,
+                BerechtigungsWert::TriState(TriState::Grant),
+                Some(lobby.inner()),
+            )
+            .await
+            .unwrap();
+
+        let spaeter = Instant::now() + SCHWELLE;
+        assert_eq!(
+            idle_clients_verschieben(&state, spaeter).await,
+            vec![normal]
+        );
+        assert_eq!(state.presence.channel_von_client(&befreit), Some(lobby));
+    }
+
+    #[tokio::test]
+    async fn aktivitaet_setzt_die_uhr_zurueck() {
+        let afk = ChannelId::new();
+        let lobby = ChannelId::new();
+        let state = state(afk).await;
+        let user = client_anmelden(&state, lobby);
+
+        let start = state.aktivitaet.letzte_aktivitaet(&user).unwrap();
+
+        let aktiv = start + Duration::from_secs(200);
+        state.aktivitaet.melden_um(user, aktiv);
+        assert!(idle_clients_verschieben(&state, start + SCHWELLE)
+            .await
+            .is_empty());
+
+        assert_eq!(
+            idle_clients_verschieben(&state, aktiv + SCHWELLE).await,
+            vec![user]
+        );
+    }
+
+    #[tokio::test]
+    async fn deaktivierte_richtlinie_verschiebt_nicht() {
+        let afk = ChannelId::new();
+        let state = state(afk).await;
+        let user = client_anmelden(&state, ChannelId::new());
+
+        state.afk.richtlinie_aendern(Some(Duration::ZERO), None);
+        assert!(
+            idle_clients_verschieben(&state, Instant::now() + SCHWELLE * 10)
+                .await
+                .is_empty()
+        );
+        assert_ne!(state.presence.channel_von_client(&user), Some(afk));
+    }
+
+    #[test]
+    fn richtlinie_ohne_kanal_ist_inaktiv() {
+        let r = AfkRichtlinie {
+            schwelle: SCHWELLE,
+            afk_kanal: None,
+        };
+        assert!(r.aktiver_kanal().is_none());
+    }
+}
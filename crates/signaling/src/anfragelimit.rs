@@ -0,0 +1,348 @@
+//! Begrenzung gleichzeitig laufender Anfragen im Dispatcher
+//!
+//! Damit ein einzelner Client mit vielen parallelen Anfragen andere nicht
+//! aushungert, zaehlt der [`AnfrageBegrenzer`] auf drei Ebenen:
+//! - pro Verbindung ([`VerbindungsAnfragen`], im `DispatcherContext`)
+//! - pro Benutzer ueber alle seine Verbindungen
+//! - serverweit fuer datenbanklastige Nachrichten (Semaphore)
+//!
+//! Ueber den ersten beiden Grenzen wird sofort mit `RateLimited` abgelehnt.
+//! Auf einen Datenbank-Platz wird bis `db_wartezeit` gewartet, damit kurze
+//! Spitzen ausgeglichen werden; erst danach folgt die Ablehnung.
+//!
+//! Ein [`AnfragePlatz`] bleibt belegt, bis die Arbeit fertig ist – auch bei
+//! schreibender Arbeit, die nach einer Zeitueberschreitung weiterlaeuft.
+
+use dashmap::DashMap;
+use speakeasy_core::types::UserId;
+use speakeasy_core::SpeakeasyError;
+use speakeasy_protocol::control::ControlMessage;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+/// Grenzen fuer gleichzeitige Anfragen (0 = unbegrenzt)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AnfrageLimits {
+    /// Gleichzeitige Anfragen einer Verbindung
+    pub pro_verbindung: usize,
+    /// Gleichzeitige Anfragen eines Benutzers ueber alle Verbindungen
+    pub pro_benutzer: usize,
+    /// Gleichzeitige datenbanklastige Anfragen serverweit
+    pub db_gleichzeitig: usize,
+    /// So lange wartet eine datenbanklastige Anfrage auf einen freien Platz
+    pub db_wartezeit: Duration,
+    /// Wiederholungshinweis in abgelehnten Antworten
+    pub wiederholen_nach_sek: u64,
+}
+
+impl Default for AnfrageLimits {
+    fn default() -> Self {
+        Self {
+            pro_verbindung: 8,
+            pro_benutzer: 16,
+            db_gleichzeitig: 32,
+            db_wartezeit: Duration::from_millis(500),
+            wiederholen_nach_sek: 1,
+        }
+    }
+}
+
+/// Grund einer Ablehnung
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Ablehnung {
+    Verbindung,
+    Benutzer,
+    Datenbank,
+}
+
+impl Ablehnung {
+    /// Kontext fuer die Fehlermeldung
+    pub fn kontext(self) -> &'static str {
+        match self {
+            Self::Verbindung => "Zu viele gleichzeitige Anfragen auf dieser Verbindung",
+            Self::Benutzer => "Zu viele gleichzeitige Anfragen dieses Benutzers",
+            Self::Datenbank => "Server ausgelastet",
+        }
+    }
+}
+
+/// Laufende Anfragen einer Verbindung (Clone teilt den Zaehler)
+#[derive(Debug, Clone, Default)]
+pub struct VerbindungsAnfragen(Arc<AtomicUsize>);
+
+impl VerbindungsAnfragen {
+    pub fn neu() -> Self {
+        Self::default()
+    }
+
+    /// Anzahl aktuell laufender Anfragen
+    pub fn laufend(&self) -> usize {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// Momentaufnahme fuer die Metriken
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct AnfrageStatistik {
+    /// Aktuell laufende (gezaehlte) Anfragen
+    pub laufend: usize,
+    /// Davon mit belegtem Datenbank-Platz
+    pub db_laufend: usize,
+    /// Abgelehnte Anfragen seit dem Start
+    pub abgelehnt: u64,
+}
+
+struct Innen {
+    limits: AnfrageLimits,
+    pro_benutzer: DashMap<UserId, usize>,
+    db: Option<Arc<Semaphore>>,
+    laufend: AtomicUsize,
+    db_laufend: AtomicUsize,
+    abgelehnt: AtomicU64,
+}
+
+/// Zaehlt laufende Anfragen und vergibt Plaetze (Clone teilt den Zustand)
+#[derive(Clone)]
+pub struct AnfrageBegrenzer {
+    innen: Arc<Innen>,
+}
+
+impl AnfrageBegrenzer {
+    pub fn neu(limits: AnfrageLimits) -> Self {
+        let db =
+            (limits.db_gleichzeitig > 0).then(|| Arc::new(Semaphore::new(limits.db_gleichzeitig)));
+        Self {
+            innen: Arc::new(Innen {
+                limits,
+                pro_benutzer: DashMap::new(),
+                db,
+                laufend: AtomicUsize::new(0),
+                db_laufend: AtomicUsize::new(0),
+                abgelehnt: AtomicU64::new(0),
+            }),
+        }
+    }
+
+    pub fn limits(&self) -> &AnfrageLimits {
+        &self.innen.limits
+    }
+
+    /// Belegt einen Platz fuer eine Anfrage
+    ///
+    /// `user_id` ist `None` vor dem Login; dann gilt nur die Verbindungsgrenze.
+    /// `datenbank` markiert datenbanklastige Anfragen, die zusaetzlich einen
+    /// serverweiten Platz brauchen.
+    pub async fn belegen(
+        &self,
+        verbindung: &VerbindungsAnfragen,
+        user_id: Option<UserId>,
+        datenbank: bool,
+    ) -> Result<AnfragePlatz, Ablehnung> {
+        let limits = &self.innen.limits;
+        let mut platz = AnfragePlatz {
+            begrenzer: self.clone(),
+            verbindung: None,
+            benutzer: None,
+            db: None,
+            gezaehlt: false,
+        };
+
+        let vorher = verbindung.0.fetch_add(1, Ordering::AcqRel);
+        platz.verbindung = Some(Arc::clone(&verbindung.0));
+        if limits.pro_verbindung > 0 && vorher >= limits.pro_verbindung {
+            return Err(self.ablehnen(Ablehnung::Verbindung));
+        }
+
+        if let Some(uid) = user_id {
+            let vorher = {
+                let mut zaehler = self.innen.pro_benutzer.entry(uid).or_insert(0);
+                *zaehler += 1;
+                *zaehler - 1
+            };
+            platz.benutzer = Some(uid);
+            if limits.pro_benutzer > 0 && vorher >= limits.pro_benutzer {
+                return Err(self.ablehnen(Ablehnung::Benutzer));
+            }
+        }
+
+        if let Some(semaphore) = self.innen.db.as_ref().filter(|_| datenbank) {
+            let erlaubnis =
+                tokio::time::timeout(limits.db_wartezeit, Arc::clone(semaphore).acquire_owned())
+                    .await;
+            match erlaubnis {
+                Ok(Ok(erlaubnis)) => {
+                    self.innen.db_laufend.fetch_add(1, Ordering::Relaxed);
+                    platz.db = Some(erlaubnis);
+                }
+                // Die Semaphore wird nie geschlossen
+                Ok(Err(_)) | Err(_) => return Err(self.ablehnen(Ablehnung::Datenbank)),
+            }
+        }
+
+        self.innen.laufend.fetch_add(1, Ordering::Relaxed);
+        platz.gezaehlt = true;
+        Ok(platz)
+    }
+
+    /// `RateLimited`-Antwort mit Wiederholungshinweis
+    pub fn ablehnung_antwort(&self, request_id: u32, grund: Ablehnung) -> ControlMessage {
+        ControlMessage::fehler_mit_kontext(
+            request_id,
+            grund.kontext(),
+            SpeakeasyError::RateLimit {
+                retry_after_secs: self.innen.limits.wiederholen_nach_sek,
+            },
+        )
+    }
+
+    /// Laufende Anfragen eines Benutzers ueber alle Verbindungen
+    pub fn laufend_fuer(&self, user_id: &UserId) -> usize {
+        self.innen
+            .pro_benutzer
+            .get(user_id)
+            .map(|z| *z)
+            .unwrap_or(0)
+    }
+
+    pub fn statistik(&self) -> AnfrageStatistik {
+        AnfrageStatistik {
+            laufend: self.innen.laufend.load(Ordering::Relaxed),
+            db_laufend: self.innen.db_laufend.load(Ordering::Relaxed),
+            abgelehnt: self.innen.abgelehnt.load(Ordering::Relaxed),
+        }
+    }
+
+    fn ablehnen(&self, grund: Ablehnung) -> Ablehnung {
+        self.innen.abgelehnt.fetch_add(1, Ordering::Relaxed);
+        grund
+    }
+}
+
+/// Belegte Plaetze einer Anfrage; werden beim Drop freigegeben
+pub struct AnfragePlatz {
+    begrenzer: AnfrageBegrenzer,
+    verbindung: Option<Arc<AtomicUsize>>,
+    benutzer: Option<UserId>,
+    db: Option<OwnedSemaphorePermit>,
+    /// Vollstaendig belegt und in `laufend` gezaehlt
+    gezaehlt: bool,
+}
+
+impl Drop for AnfragePlatz {
+    fn drop(&mut self) {
+        let innen = &self.begrenzer.innen;
+        if let Some(verbindung) = self.verbindung.take() {
+            verbindung.fetch_sub(1, Ordering::AcqRel);
+        }
+        if let Some(uid) = self.benutzer.take() {
+            innen.pro_benutzer.remove_if_mut(&uid, |_, zaehler| {
+                *zaehler -= 1;
+                *zaehler == 0
+            });
+        }
+        if self.db.take().is_some() {
+            innen.db_laufend.fetch_sub(1, Ordering::Relaxed);
+        }
+        // Abgelehnte Anfragen bauen nur ihre Teilzaehler ab
+        if self.gezaehlt {
+            innen.laufend.fetch_sub(1, Ordering::Relaxed);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limits(pro_verbindung: usize, pro_benutzer: usize, db_gleichzeitig: usize) -> AnfrageLimits {
+        AnfrageLimits {
+            pro_verbindung,
+            pro_benutzer,
+            db_gleichzeitig,
+            db_wartezeit: Duration::from_millis(20),
+            wiederholen_nach_sek: 2,
+        }
+    }
+
+    #[tokio::test]
+    async fn verbindungsgrenze_und_freigabe() {
+        let begrenzer = AnfrageBegrenzer::neu(limits(2, 0, 0));
+        let verbindung = VerbindungsAnfragen::neu();
+
+        let erster = begrenzer.belegen(&verbindung, None, false).await.unwrap();
+        let _zweiter = begrenzer.belegen(&verbindung, None, false).await.unwrap();
+        assert_eq!(
+            begrenzer.belegen(&verbindung, None, false).await.err(),
+            Some(Ablehnung::Verbindung)
+        );
+        assert_eq!(verbindung.laufend(), 2);
+
+        drop(erster);
+        assert!(begrenzer.belegen(&verbindung, None, false).await.is_ok());
+        assert_eq!(begrenzer.statistik().abgelehnt, 1);
+    }
+
+    #[tokio::test]
+    async fn benutzergrenze_gilt_ueber_verbindungen() {
+        let begrenzer = AnfrageBegrenzer::neu(limits(8, 3, 0));
+        let anna = UserId::new();
+        let (a, b) = (VerbindungsAnfragen::neu(), VerbindungsAnfragen::neu());
+
+        let mut plaetze = Vec::new();
+        plaetze.push(begrenzer.belegen(&a, Some(anna), false).await.unwrap());
+        plaetze.push(begrenzer.belegen(&a, Some(anna), false).await.unwrap());
+        plaetze.push(begrenzer.belegen(&b, Some(anna), false).await.unwrap());
+        assert_eq!(
+            begrenzer.belegen(&b, Some(anna), false).await.err(),
+            Some(Ablehnung::Benutzer)
+        );
+        // Andere Benutzer sind nicht betroffen
+        assert!(begrenzer
+            .belegen(&b, Some(UserId::new()), false)
+            .await
+            .is_ok());
+
+        assert_eq!(begrenzer.laufend_fuer(&anna), 3);
+        assert_eq!(b.laufend(), 1);
+        plaetze.clear();
+        assert_eq!(begrenzer.laufend_fuer(&anna), 0);
+        assert_eq!(begrenzer.statistik().laufend, 0);
+    }
+
+    #[tokio::test]
+    async fn datenbankplaetze_werden_kurz_abgewartet() {
+        let begrenzer = AnfrageBegrenzer::neu(limits(0, 0, 1));
+        let verbindung = VerbindungsAnfragen::neu();
+
+        let belegt = begrenzer.belegen(&verbindung, None, true).await.unwrap();
+        assert_eq!(begrenzer.statistik().db_laufend, 1);
+        // Leichte Anfragen brauchen keinen Datenbank-Platz
+        assert!(begrenzer.belegen(&verbindung, None, false).await.is_ok());
+        assert_eq!(
+            begrenzer.belegen(&verbindung, None, true).await.err(),
+            Some(Ablehnung::Datenbank)
+        );
+
+        // Wird der Platz innerhalb der Wartezeit frei, kommt die Anfrage durch
+        let wartend = begrenzer.belegen(&verbindung, None, true);
+        tokio::pin!(wartend);
+        assert!(futures_util::poll!(wartend.as_mut()).is_pending());
+        drop(belegt);
+        assert!(wartend.await.is_ok());
+        assert_eq!(verbindung.laufend(), 0);
+
+        let antwort = begrenzer.ablehnung_antwort(3, Ablehnung::Datenbank);
+        match antwort.payload {
+            speakeasy_protocol::control::ControlPayload::Error(fehler) => {
+                assert_eq!(
+                    fehler.code,
+                    speakeasy_protocol::control::ErrorCode::RateLimited
+                );
+                assert_eq!(fehler.details.unwrap()["retry_after_secs"], 2);
+            }
+            andere => panic!("Erwartet Error, erhalten: {andere:?}"),
+        }
+    }
+}
@@ -0,0 +1,124 @@
+//! Server-Ankuendigungen an Administratoren
+//!
+//! Der Server meldet Betriebsalarme als `ServerAnnouncement`. Empfaenger
+//! sind alle verbundenen Clients, denen [`ALARME_EMPFANGEN`] serverweit
+//! ausdruecklich gewaehrt ist (ueblicherweise ueber die Admin-Servergruppe);
+//! ohne Regel erhaelt niemand Alarme.
+
+use speakeasy_core::types::ChannelId;
+use speakeasy_db::{
+    repository::UserRepository, BanRepository, ChannelRepository, ChatMessageRepository,
+    PermissionRepository, ServerGroupRepository,
+};
+use speakeasy_protocol::control::{ControlMessage, ControlPayload, ServerAnnouncement};
+
+use crate::notfall::ausdruecklich_gewaehrt;
+use crate::server_state::SignalingState;
+
+/// Berechtigung zum Empfang von Betriebsalarmen
+pub const ALARME_EMPFANGEN: &str = "b_server_alerts_receive";
+
+/// Sendet eine Ankuendigung an alle verbundenen Administratoren
+///
+/// Gibt die Anzahl der Empfaenger zurueck.
+pub async fn an_administratoren_senden<U, P, B>(
+    state: &SignalingState<U, P, B>,
+    ankuendigung: ServerAnnouncement,
+) -> usize
+where
+    U: UserRepository + ServerGroupRepository + ChannelRepository + ChatMessageRepository + 'static,
+    P: PermissionRepository + 'static,
+    B: BanRepository + 'static,
+{
+    let server = ChannelId(uuid::Uuid::nil());
+    let mut empfaenger = 0;
+    for client in state.presence.alle_clients() {
+        if !ausdruecklich_gewaehrt(state, client.user_id, server, ALARME_EMPFANGEN).await {
+            continue;
+        }
+        let nachricht =
+            ControlMessage::new(0, ControlPayload::ServerAnnouncement(ankuendigung.clone()));
+        if state.broadcaster.an_user_senden(&client.user_id, nachricht) {
+            empfaenger += 1;
+        }
+    }
+    empfaenger
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_hilfen::{client_anmelden, test_state, TestState};
+    use speakeasy_core::types::UserId;
+    use speakeasy_db::models::{
+        BerechtigungsWert, BerechtigungsZiel, NeueServerGruppe, NeuerBenutzer, TriState,
+    };
+
+    use speakeasy_protocol::control::AnnouncementSeverity;
+
+    async fn verbinden(state: &TestState, name: &str) -> UserId {
+        let user_id = UserId(
+            UserRepository::create(
+                state.db.as_ref(),
+                NeuerBenutzer {
+                    username: name,
+                    password_hash: "hash",
+                },
+            )
+            .await
+            .unwrap()
+            .id,
+        );
+        client_anmelden(state, user_id, None);
+        user_id
+    }
+
+    #[tokio::test]
+    async fn nur_administratoren_erhalten_alarme() {
+        let state = test_state().await;
+        let admin = verbinden(&state, "admin").await;
+        let gast = verbinden(&state, "gast").await;
+        let mut admin_rx = state.broadcaster.client_registrieren(admin);
+        let mut gast_rx = state.broadcaster.client_registrieren(gast);
+
+        let gruppe = ServerGroupRepository::create(
+            state.db.as_ref(),
+            NeueServerGruppe {
+                name: "Admin",
+                priority: 100,
+                is_default: false,
+            },
+        )
+        .await
+        .unwrap();
+        ServerGroupRepository::add_member(state.db.as_ref(), gruppe.id, admin.inner())
+            .await
+            .unwrap();
+        state
+            .db
+            .set_permission(
+                &BerechtigungsZiel::ServerGruppe(gruppe.id),
+                ALARME_EMPFANGEN,
+                BerechtigungsWert::TriState(TriState::Grant),
+                None,
+            )
+            .await
+            .unwrap();
+
+        let ankuendigung = ServerAnnouncement {
+            severity: AnnouncementSeverity::Warning,
+            title: "speicher_fast_voll".into(),
+            message: "Speicher zu 93 % belegt".into(),
+            resolved: false,
+        };
+        assert_eq!(
+            an_administratoren_senden(&state, ankuendigung.clone()).await,
+            1
+        );
+        match admin_rx.try_recv().unwrap().payload {
+            ControlPayload::ServerAnnouncement(erhalten) => assert_eq!(erhalten, ankuendigung),
+            andere => panic!("unerwartet: {andere:?}"),
+        }
+        assert!(gast_rx.try_recv().is_err());
+    }
+}
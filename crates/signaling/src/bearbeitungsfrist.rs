@@ -0,0 +1,284 @@
+//! Bearbeitungsfrist – wie lange Benutzer eigene Chat-Nachrichten aendern duerfen
+//!
+//! Die Frist gilt fuer Bearbeiten und Loeschen gleichermassen und zaehlt ab
+//! `created_at` der Nachricht. Ein Kanal kann mit `edit_window_secs` vom
+//! Server-Standard (`ServerEinstellungen::chat_bearbeitungsfrist_sek`)
+//! abweichen; 0 bedeutet jeweils unbegrenzt. Nach Ablauf lehnt der
+//! Chat-Service mit `chat.edit_window_expired` ab.
+//!
+//! Mitglieder mit ausdruecklich gewaehrtem [`CHAT_MODERATION`] sind von der
+//! Frist ausgenommen und duerfen auch fremde Nachrichten loeschen.
+
+use speakeasy_chat::AenderungsRecht;
+use speakeasy_core::types::{ChannelId, UserId};
+use speakeasy_db::{
+    models::MAX_BEARBEITUNGSFRIST_SEK, repository::UserRepository, BanRepository,
+    ChannelRepository, ChatMessageRepository, PermissionRepository, ServerGroupRepository,
+};
+use uuid::Uuid;
+
+use crate::error::{SignalingError, SignalingResult};
+use crate::notfall::ausdruecklich_gewaehrt;
+use crate::server_state::SignalingState;
+
+/// Berechtigung fuer Chat-Moderatoren
+pub const CHAT_MODERATION: &str = "b_chat_moderate";
+
+/// Wirksame Frist eines Kanals in Sekunden (0 = unbegrenzt)
+///
+/// `kanal` ist `edit_window_secs` des Kanals; `None` (keine Ueberschreibung
+/// oder unbekannter Kanal) uebernimmt den Server-Standard.
+pub fn wirksame_frist(kanal: Option<i64>, standard: u32) -> u32 {
+    match kanal {
+        Some(sekunden) => sekunden.clamp(0, MAX_BEARBEITUNGSFRIST_SEK as i64) as u32,
+        None => standard,
+    }
+}
+
+/// Ermittelt, was `user_id` mit der Nachricht `message_id` darf
+///
+/// Unbekannte Nachrichten ergeben das Standard-Recht; der Chat-Service
+/// meldet sie anschliessend als nicht gefunden.
+pub async fn recht_ermitteln<U, P, B>(
+    state: &SignalingState<U, P, B>,
+    user_id: UserId,
+    message_id: Uuid,
+) -> SignalingResult<AenderungsRecht>
+where
+    U: UserRepository + ServerGroupRepository + ChannelRepository + ChatMessageRepository + 'static,
+    P: PermissionRepository + 'static,
+    B: BanRepository + 'static,
+{
+    let db = state.db.as_ref();
+    let Some(nachricht) = ChatMessageRepository::get_by_id(db, message_id)
+        .await
+        .map_err(|e| SignalingError::intern(e.to_string()))?
+    else {
+        return Ok(AenderungsRecht::default());
+    };
+    let channel_id = ChannelId(nachricht.channel_id);
+
+    if ausdruecklich_gewaehrt(state, user_id, channel_id, CHAT_MODERATION).await {
+        return Ok(AenderungsRecht {
+            frist_sek: None,
+            moderator: true,
+        });
+    }
+
+    let kanal = ChannelRepository::get_by_id(db, channel_id.inner())
+        .await
+        .map_err(|e| SignalingError::intern(e.to_string()))?;
+    let frist = wirksame_frist(
+        kanal.and_then(|k| k.edit_window_secs),
+        state.einstellungen.aktuell().chat_bearbeitungsfrist_sek,
+    );
+    Ok(AenderungsRecht {
+        frist_sek: (frist > 0).then_some(frist),
+        moderator: false,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::handlers::channel_handler::kanal_geaendert_melden;
+    use crate::handlers::chat_handler::{handle_chat_delete, handle_chat_edit};
+    use crate::server_state::SignalingConfig;
+    use crate::test_hilfen::{TestAufbau, TestState};
+    use speakeasy_db::models::{
+        BerechtigungsWert, BerechtigungsZiel, KanalUpdate, NachrichtenTyp, NeueNachricht,
+        NeuerBenutzer, NeuerKanal, TriState,
+    };
+    use speakeasy_protocol::control::{
+        ChatDeleteRequest, ChatEditRequest, ControlPayload, ErrorCode,
+    };
+
+    async fn state(standard_frist: u32) -> TestState {
+        TestAufbau::default()
+            .config(SignalingConfig {
+                chat_bearbeitungsfrist_sek: standard_frist,
+                ..Default::default()
+            })
+            .bauen()
+            .await
+    }
+
+    async fn benutzer(state: &TestState, name: &str) -> UserId {
+        let benutzer = UserRepository::create(
+            state.db.as_ref(),
+            NeuerBenutzer {
+                username: name,
+                password_hash: "hash",
+            },
+        )
+        .await
+        .unwrap();
+        UserId(benutzer.id)
+    }
+
+    /// Kanal mit optionaler Frist und eine Nachricht von `sender`
+    async fn nachricht(state: &TestState, frist: Option<i64>, sender: UserId) -> (Uuid, ChannelId) {
+        let db = state.db.as_ref();
+        let name = format!("Plaudern {}", Uuid::new_v4());
+        let kanal = ChannelRepository::create(
+            db,
+            NeuerKanal {
+                name: &name,
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        ChannelRepository::update(
+            db,
+            kanal.id,
+            KanalUpdate {
+                edit_window_secs: Some(frist),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        let nachricht = ChatMessageRepository::create(
+            db,
+            NeueNachricht {
+                channel_id: kanal.id,
+                sender_id: sender.inner(),
+                content: "Hallo",
+                message_type: NachrichtenTyp::Text,
+                reply_to: None,
+            },
+        )
+        .await
+        .unwrap();
+        (nachricht.id, ChannelId(kanal.id))
+    }
+
+    async fn moderator_machen(state: &TestState, user_id: UserId, kanal: ChannelId) {
+        state
+            .db
+            .set_permission(
+                &BerechtigungsZiel::Benutzer(user_id.inner()),
+                CHAT_MODERATION,
+                BerechtigungsWert::TriState(TriState::Grant),
+                Some(kanal.inner()),
+            )
+            .await
+            .unwrap();
+    }
+
+    #[test]
+    fn kanal_ueberschreibt_server_standard() {
+        assert_eq!(wirksame_frist(None, 900), 900);
+        assert_eq!(wirksame_frist(Some(60), 900), 60);
+        // 0 im Kanal hebt einen Server-Standard auf
+        assert_eq!(wirksame_frist(Some(0), 900), 0);
+        assert_eq!(wirksame_frist(Some(-5), 900), 0);
+        assert_eq!(wirksame_frist(Some(i64::MAX), 0), MAX_BEARBEITUNGSFRIST_SEK);
+    }
+
+    #[tokio::test]
+    async fn recht_folgt_kanal_und_server_standard() {
+        let state = state(900).await;
+        let sender = benutzer(&state, "sender").await;
+
+        let (standard, _) = nachricht(&state, None, sender).await;
+        let recht = recht_ermitteln(&state, sender, standard).await.unwrap();
+        assert_eq!(recht.frist_sek, Some(900));
+        assert!(!recht.moderator);
+
+        let (eigene, _) = nachricht(&state, Some(60), sender).await;
+        let recht = recht_ermitteln(&state, sender, eigene).await.unwrap();
+        assert_eq!(recht.frist_sek, Some(60));
+
+        let (unbegrenzt, _) = nachricht(&state, Some(0), sender).await;
+        let recht = recht_ermitteln(&state, sender, unbegrenzt).await.unwrap();
+        assert_eq!(recht.frist_sek, None);
+
+        // Unbekannte Nachricht: Standard-Recht, der Service meldet NotFound
+        let recht = recht_ermitteln(&state, sender, Uuid::new_v4())
+            .await
+            .unwrap();
+        assert_eq!(recht, AenderungsRecht::default());
+    }
+
+    #[tokio::test]
+    async fn moderator_ist_von_der_frist_ausgenommen() {
+        let state = state(900).await;
+        let sender = benutzer(&state, "sender").await;
+        let (id, kanal) = nachricht(&state, Some(60), sender).await;
+        moderator_machen(&state, sender, kanal).await;
+
+        let recht = recht_ermitteln(&state, sender, id).await.unwrap();
+        assert_eq!(
+            recht,
+            AenderungsRecht {
+                frist_sek: None,
+                moderator: true,
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn nur_moderatoren_loeschen_fremde_nachrichten() {
+        let state = state(0).await;
+        let sender = benutzer(&state, "sender").await;
+        let fremder = benutzer(&state, "fremder").await;
+        let moderator = benutzer(&state, "moderator").await;
+        let (id, kanal) = nachricht(&state, Some(60), sender).await;
+        moderator_machen(&state, moderator, kanal).await;
+        let anfrage = || ChatDeleteRequest {
+            message_id: id.to_string(),
+        };
+
+        match handle_chat_delete(anfrage(), 1, fremder, &state)
+            .await
+            .payload
+        {
+            ControlPayload::Error(fehler) => assert_eq!(fehler.code, ErrorCode::PermissionDenied),
+            andere => panic!("unerwartet: {andere:?}"),
+        }
+
+        let antwort = handle_chat_delete(anfrage(), 2, moderator, &state).await;
+        assert!(matches!(antwort.payload, ControlPayload::ChatDelete(_)));
+    }
+
+    #[tokio::test]
+    async fn bearbeiten_innerhalb_der_frist_zaehlt_mit() {
+        let state = state(0).await;
+        let sender = benutzer(&state, "sender").await;
+        let (id, _) = nachricht(&state, Some(60), sender).await;
+
+        let anfrage = ChatEditRequest {
+            message_id: id.to_string(),
+            content: "Hallo zusammen".into(),
+        };
+        let antwort = handle_chat_edit(anfrage, 1, sender, &state).await;
+        assert!(matches!(antwort.payload, ControlPayload::ChatEdit(_)));
+
+        let gespeichert = ChatMessageRepository::get_by_id(state.db.as_ref(), id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(gespeichert.edit_count, 1);
+    }
+
+    #[tokio::test]
+    async fn kanalinfo_meldet_wirksame_frist() {
+        let state = state(900).await;
+        let sender = benutzer(&state, "sender").await;
+        let (_, eigene) = nachricht(&state, Some(60), sender).await;
+        let (_, standard) = nachricht(&state, None, sender).await;
+        let mut rx = state.broadcaster.client_registrieren(sender);
+
+        for (kanal, erwartet) in [(eigene, 60), (standard, 900)] {
+            kanal_geaendert_melden(&state, kanal).await.unwrap();
+            match rx.try_recv().unwrap().payload {
+                ControlPayload::ChannelEdited(event) => {
+                    assert_eq!(event.channel.edit_window_secs, erwartet);
+                }
+                andere => panic!("unerwartet: {andere:?}"),
+            }
+        }
+    }
+}
@@ -0,0 +1,503 @@
+//! Inhaltsbereinigung – Nachrichten und Dateien eines Benutzers entfernen
+//!
+//! Nach einem Spam-Bann (oder ueber den Commander auch ohne Bann) werden
+//! alle Nachrichten und Dateien eines Benutzers aus einem Zeitfenster weich
+//! geloescht, auf Wunsch nur in einem Kanal. Die Arbeit laeuft in Stapeln
+//! ueber den [`KontoDienst`]: jeder Stapel ist eine Transaktion, gibt das
+//! Kontingent frei und schreibt den Fortschritt des Auftrags fort. Bricht
+//! ein Lauf ab, setzt [`fortsetzen`] mit der Auftrags-ID dort fort.
+//!
+//! Verbundene Clients erhalten pro Stapel und Kanal ein `ChatBulkDeleted`
+//! mit den geloeschten Nachrichten; am Ende steht ein einziger
+//! Audit-Eintrag mit den Zahlen pro Kanal.
+
+use chrono::Utc;
+use speakeasy_chat::{ChatError, KontoDienst};
+use speakeasy_core::types::{ChannelId, UserId};
+use speakeasy_db::{
+    models::{NeueInhaltsBereinigung, NeuerAuditEintrag},
+    repository::UserRepository,
+    AuditLogRepository, BanRepository, ChannelRepository, ChatMessageRepository, DbError,
+    PermissionRepository, ServerGroupRepository,
+};
+use speakeasy_protocol::control::{ChatBulkDeletedEvent, ControlMessage, ControlPayload};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::Duration;
+use uuid::Uuid;
+
+use crate::error::{SignalingError, SignalingResult};
+use crate::server_state::SignalingState;
+
+/// Hoechstzahl von Nachrichten (und Dateien) pro Stapel
+pub const STAPEL_GROESSE: i64 = 200;
+
+/// Auftrag fuer eine neue Bereinigung
+#[derive(Debug, Clone)]
+pub struct Bereinigungsauftrag {
+    pub user_id: UserId,
+    /// Rueckblick ab jetzt: nur juenger erstellte Inhalte werden entfernt
+    pub zeitraum: Duration,
+    /// Nur in diesem Kanal (`None` = alle Kanaele)
+    pub nur_kanal: Option<ChannelId>,
+    /// Ausloesender Moderator (`None` = System)
+    pub aktor_id: Option<UserId>,
+}
+
+/// In einem Kanal geloeschte Inhalte
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct KanalBereinigung {
+    pub nachrichten: u64,
+    pub dateien: u64,
+}
+
+/// Ergebnis eines Bereinigungslaufs
+#[derive(Debug, Clone)]
+pub struct Bereinigungsergebnis {
+    /// Auftrags-ID, mit der ein abgebrochener Lauf fortgesetzt wird
+    pub job_id: Uuid,
+    pub user_id: UserId,
+    /// Insgesamt geloeschte Nachrichten (auch aus frueheren Laeufen)
+    pub nachrichten: u64,
+    /// Insgesamt geloeschte Dateien (auch aus frueheren Laeufen)
+    pub dateien: u64,
+    /// In diesem Lauf geloescht, pro Kanal
+    pub pro_kanal: BTreeMap<Uuid, KanalBereinigung>,
+    /// Zugestellte `ChatBulkDeleted`-Events
+    pub benachrichtigt: usize,
+}
+
+/// Legt einen Bereinigungsauftrag an und fuehrt ihn aus
+pub async fn bereinigen<U, P, B>(
+    state: &Arc<SignalingState<U, P, B>>,
+    auftrag: Bereinigungsauftrag,
+) -> SignalingResult<Bereinigungsergebnis>
+where
+    U: UserRepository
+        + ServerGroupRepository
+        + ChannelRepository
+        + ChatMessageRepository
+        + AuditLogRepository
+        + 'static,
+    P: PermissionRepository + 'static,
+    B: BanRepository + 'static,
+{
+    let dienst = dienst(state)?;
+    let zeitraum = chrono::Duration::from_std(auftrag.zeitraum)
+        .map_err(|_| SignalingError::protokoll("Zeitraum zu gross"))?;
+    let record = dienst
+        .bereinigung_anlegen(NeueInhaltsBereinigung {
+            user_id: auftrag.user_id.inner(),
+            seit: Utc::now() - zeitraum,
+            channel_id: auftrag.nur_kanal.map(|k| k.inner()),
+            angefordert_von: auftrag.aktor_id.map(|a| a.inner()),
+        })
+        .await
+        .map_err(fehler)?;
+    tracing::info!(
+        job_id = %record.id,
+        user_id = %auftrag.user_id,
+        seit = %record.seit,
+        "Inhaltsbereinigung gestartet"
+    );
+
+    ausfuehren(state, dienst, record.id, auftrag.aktor_id).await
+}
+
+/// Setzt einen (abgebrochenen) Bereinigungsauftrag fort
+///
+/// Bereits geloeschte Inhalte werden nicht erneut gezaehlt; fuer einen
+/// abgeschlossenen Auftrag ist der Lauf leer.
+pub async fn fortsetzen<U, P, B>(
+    state: &Arc<SignalingState<U, P, B>>,
+    job_id: Uuid,
+    aktor_id: Option<UserId>,
+) -> SignalingResult<Bereinigungsergebnis>
+where
+    U: UserRepository
+        + ServerGroupRepository
+        + ChannelRepository
+        + ChatMessageRepository
+        + AuditLogRepository
+        + 'static,
+    P: PermissionRepository + 'static,
+    B: BanRepository + 'static,
+{
+    let dienst = dienst(state)?;
+    let record = dienst.bereinigung_laden(job_id).await.map_err(fehler)?;
+    let aktor_id = aktor_id.or(record.angefordert_von.map(UserId));
+    tracing::info!(
+        job_id = %job_id,
+        bisher_nachrichten = record.nachrichten,
+        bisher_dateien = record.dateien,
+        "Inhaltsbereinigung fortgesetzt"
+    );
+
+    ausfuehren(state, dienst, job_id, aktor_id).await
+}
+
+async fn ausfuehren<U, P, B>(
+    state: &Arc<SignalingState<U, P, B>>,
+    dienst: &Arc<dyn KontoDienst>,
+    job_id: Uuid,
+    aktor_id: Option<UserId>,
+) -> SignalingResult<Bereinigungsergebnis>
+where
+    U: UserRepository
+        + ServerGroupRepository
+        + ChannelRepository
+        + ChatMessageRepository
+        + AuditLogRepository
+        + 'static,
+    P: PermissionRepository + 'static,
+    B: BanRepository + 'static,
+{
+    let mut pro_kanal: BTreeMap<Uuid, KanalBereinigung> = BTreeMap::new();
+    let mut benachrichtigt = 0;
+    let mut stapel_nr = 0u32;
+
+    loop {
+        let stapel = dienst
+            .bereinigung_stapel(job_id, STAPEL_GROESSE)
+            .await
+            .map_err(fehler)?;
+        if stapel.ist_leer() {
+            break;
+        }
+        stapel_nr += 1;
+
+        // Ein Event pro Kanal und Stapel: einzelne `ChatDeleted` wuerden
+        // die Send-Queues der Clients bei grossen Mengen ueberlaufen lassen
+        let mut geloescht: BTreeMap<Uuid, Vec<String>> = BTreeMap::new();
+        for nachricht in &stapel.nachrichten {
+            pro_kanal
+                .entry(nachricht.channel_id)
+                .or_default()
+                .nachrichten += 1;
+            geloescht
+                .entry(nachricht.channel_id)
+                .or_default()
+                .push(nachricht.id.to_string());
+        }
+        for (kanal, message_ids) in geloescht {
+            let channel_id = ChannelId(kanal);
+            benachrichtigt += state.broadcaster.an_channel_senden(
+                &channel_id,
+                ControlMessage::new(
+                    0,
+                    ControlPayload::ChatBulkDeleted(ChatBulkDeletedEvent {
+                        channel_id,
+                        message_ids,
+                    }),
+                ),
+            );
+        }
+        for datei in &stapel.dateien {
+            pro_kanal.entry(datei.channel_id).or_default().dateien += 1;
+        }
+
+        tracing::info!(
+            job_id = %job_id,
+            stapel = stapel_nr,
+            nachrichten = stapel.nachrichten.len(),
+            dateien = stapel.dateien.len(),
+            "Inhaltsbereinigung: Stapel geloescht"
+        );
+    }
+
+    let record = dienst
+        .bereinigung_abschliessen(job_id)
+        .await
+        .map_err(fehler)?;
+
+    let kanaele: serde_json::Map<String, serde_json::Value> = pro_kanal
+        .iter()
+        .map(|(kanal, anzahl)| {
+            (
+                kanal.to_string(),
+                serde_json::json!({
+                    "nachrichten": anzahl.nachrichten,
+                    "dateien": anzahl.dateien,
+                }),
+            )
+        })
+        .collect();
+    state
+        .audit_protokollieren(NeuerAuditEintrag::neu(
+            aktor_id.map(|a| a.inner()),
+            "inhalte.bereinigt",
+            Some("user"),
+            Some(&record.user_id.to_string()),
+            serde_json::json!({
+                "job_id": job_id,
+                "seit": record.seit,
+                "nur_kanal": record.channel_id,
+                "nachrichten": record.nachrichten,
+                "dateien": record.dateien,
+                "kanaele": kanaele,
+            }),
+        ))
+        .await;
+
+    tracing::info!(
+        job_id = %job_id,
+        user_id = %record.user_id,
+        nachrichten = record.nachrichten,
+        dateien = record.dateien,
+        kanaele = pro_kanal.len(),
+        "Inhaltsbereinigung abgeschlossen"
+    );
+
+    Ok(Bereinigungsergebnis {
+        job_id,
+        user_id: UserId(record.user_id),
+        nachrichten: record.nachrichten as u64,
+        dateien: record.dateien as u64,
+        pro_kanal,
+        benachrichtigt,
+    })
+}
+
+fn dienst<U, P, B>(state: &SignalingState<U, P, B>) -> SignalingResult<&Arc<dyn KontoDienst>>
+where
+    U: UserRepository + ServerGroupRepository + ChannelRepository + ChatMessageRepository + 'static,
+    P: PermissionRepository + 'static,
+    B: BanRepository + 'static,
+{
+    state
+        .konto_dienst()
+        .ok_or_else(|| SignalingError::intern("Inhaltsbereinigung nicht verfuegbar"))
+}
+
+fn fehler(e: ChatError) -> SignalingError {
+    match e {
+        ChatError::DatenbankFehler(DbError::NichtGefunden(was)) => {
+            SignalingError::NichtGefunden(was)
+        }
+        andere => SignalingError::intern(andere.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_hilfen::{test_state, TestState};
+    use speakeasy_chat::{DiskStorage, KontoKonfig, KontoService, StorageBackend};
+    use speakeasy_db::models::{
+        AuditLogFilter, KanalTyp, NachrichtenTyp, NeueDatei, NeueNachricht, NeuerBenutzer,
+        NeuerKanal,
+    };
+    use speakeasy_db::FileRepository;
+
+    async fn state() -> (TestState, Arc<DiskStorage>) {
+        let state = test_state().await;
+        let speicher = Arc::new(DiskStorage::new(
+            std::env::temp_dir().join(format!("speakeasy-bereinigung-{}", Uuid::new_v4())),
+        ));
+        state.konto_dienst_setzen(KontoService::neu(
+            Arc::clone(&state.db),
+            Arc::clone(&speicher),
+            KontoKonfig::default(),
+        ));
+        (state, speicher)
+    }
+
+    async fn kanal(state: &TestState, name: &str) -> Uuid {
+        ChannelRepository::create(
+            state.db.as_ref(),
+            NeuerKanal {
+                name,
+                channel_type: KanalTyp::Text,
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap()
+        .id
+    }
+
+    async fn benutzer(state: &TestState, name: &str) -> Uuid {
+        UserRepository::create(
+            state.db.as_ref(),
+            NeuerBenutzer {
+                username: name,
+                password_hash: "hash",
+            },
+        )
+        .await
+        .unwrap()
+        .id
+    }
+
+    async fn schreiben(state: &TestState, kanal: Uuid, sender: Uuid, text: &str) -> Uuid {
+        ChatMessageRepository::create(
+            state.db.as_ref(),
+            NeueNachricht {
+                channel_id: kanal,
+                sender_id: sender,
+                content: text,
+                message_type: NachrichtenTyp::Text,
+                reply_to: None,
+            },
+        )
+        .await
+        .unwrap()
+        .id
+    }
+
+    fn zuhoeren(state: &TestState, kanal: Uuid) -> tokio::sync::mpsc::Receiver<ControlMessage> {
+        let user_id = UserId::new();
+        let rx = state.broadcaster.client_registrieren(user_id);
+        state
+            .broadcaster
+            .channel_beitreten(user_id, ChannelId(kanal));
+        rx
+    }
+
+    #[tokio::test]
+    async fn spam_ueber_kanaele_bis_zur_fenstergrenze() {
+        let (state, speicher) = state().await;
+        let spammer = benutzer(&state, "spammer").await;
+        let anna = benutzer(&state, "anna").await;
+        let lobby = kanal(&state, "Lobby").await;
+        let offtopic = kanal(&state, "Offtopic").await;
+
+        // Vor dem Fenster (Zeitstempel haben Sekunden-Aufloesung)
+        let alt = schreiben(&state, lobby, spammer, "Hallo zusammen").await;
+        tokio::time::sleep(Duration::from_secs(3)).await;
+
+        let mehr_als_ein_stapel = STAPEL_GROESSE as usize + 50;
+        for i in 0..mehr_als_ein_stapel {
+            schreiben(&state, lobby, spammer, &format!("Kauft jetzt! {i}")).await;
+        }
+        schreiben(&state, offtopic, spammer, "Billig!").await;
+        schreiben(&state, offtopic, spammer, "Nur heute!").await;
+        let fremd = schreiben(&state, lobby, anna, "Bitte aufhoeren").await;
+        speicher.store("offtopic/spam.exe", b"MZ").await.unwrap();
+        FileRepository::create(
+            state.db.as_ref(),
+            NeueDatei {
+                channel_id: offtopic,
+                uploader_id: spammer,
+                filename: "spam.exe",
+                mime_type: "application/octet-stream",
+                size_bytes: 2,
+                storage_path: "offtopic/spam.exe",
+                checksum: "abc",
+            },
+        )
+        .await
+        .unwrap();
+        state.db.increment_usage("default", 2).await.unwrap();
+
+        let mut lobby_a = zuhoeren(&state, lobby);
+        let _lobby_b = zuhoeren(&state, lobby);
+        let mut offtopic_rx = zuhoeren(&state, offtopic);
+
+        let ergebnis = bereinigen(
+            &state,
+            Bereinigungsauftrag {
+                user_id: UserId(spammer),
+                zeitraum: Duration::from_secs(2),
+                nur_kanal: None,
+                aktor_id: Some(UserId(anna)),
+            },
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(ergebnis.nachrichten, mehr_als_ein_stapel as u64 + 2);
+        assert_eq!(ergebnis.dateien, 1);
+        assert_eq!(
+            ergebnis.pro_kanal[&lobby],
+            KanalBereinigung {
+                nachrichten: mehr_als_ein_stapel as u64,
+                dateien: 0,
+            }
+        );
+        assert_eq!(
+            ergebnis.pro_kanal[&offtopic],
+            KanalBereinigung {
+                nachrichten: 2,
+                dateien: 1,
+            }
+        );
+        // Lobby: zwei Stapel an zwei Zuhoerer, Offtopic: ein Stapel an einen
+        assert_eq!(ergebnis.benachrichtigt, 2 * 2 + 1);
+        let mut in_lobby = 0;
+        for _ in 0..2 {
+            match lobby_a.recv().await.unwrap().payload {
+                ControlPayload::ChatBulkDeleted(event) => {
+                    assert_eq!(event.channel_id.inner(), lobby);
+                    in_lobby += event.message_ids.len();
+                }
+                andere => panic!("ChatBulkDeleted erwartet, erhalten: {andere:?}"),
+            }
+        }
+        assert_eq!(in_lobby, mehr_als_ein_stapel);
+        match offtopic_rx.recv().await.unwrap().payload {
+            ControlPayload::ChatBulkDeleted(event) => assert_eq!(event.message_ids.len(), 2),
+            andere => panic!("ChatBulkDeleted erwartet, erhalten: {andere:?}"),
+        }
+
+        for (id, bleibt) in [(alt, true), (fremd, true)] {
+            let nachricht = ChatMessageRepository::get_by_id(state.db.as_ref(), id)
+                .await
+                .unwrap()
+                .unwrap();
+            assert_eq!(nachricht.deleted_at.is_none(), bleibt);
+        }
+        assert!(speicher.retrieve("offtopic/spam.exe").await.is_err());
+        assert_eq!(
+            state.db.get_quota("default").await.unwrap().current_usage,
+            0
+        );
+
+        let audit = state
+            .db
+            .list_events(AuditLogFilter {
+                action: Some("inhalte.bereinigt".into()),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(audit.len(), 1);
+        assert_eq!(audit[0].actor_id, Some(anna));
+        assert_eq!(
+            audit[0].details["kanaele"][offtopic.to_string()]["dateien"],
+            1
+        );
+    }
+
+    #[tokio::test]
+    async fn fortsetzen_ist_idempotent() {
+        let (state, _speicher) = state().await;
+        let spammer = benutzer(&state, "spammer").await;
+        let lobby = kanal(&state, "Lobby").await;
+        schreiben(&state, lobby, spammer, "Kauft jetzt!").await;
+        let _rx = zuhoeren(&state, lobby);
+
+        let erster = bereinigen(
+            &state,
+            Bereinigungsauftrag {
+                user_id: UserId(spammer),
+                zeitraum: Duration::from_secs(3600),
+                nur_kanal: Some(ChannelId(lobby)),
+                aktor_id: None,
+            },
+        )
+        .await
+        .unwrap();
+        assert_eq!((erster.nachrichten, erster.benachrichtigt), (1, 1));
+
+        let zweiter = fortsetzen(&state, erster.job_id, None).await.unwrap();
+        assert_eq!(zweiter.nachrichten, 1);
+        assert_eq!(zweiter.benachrichtigt, 0);
+        assert!(zweiter.pro_kanal.is_empty());
+
+        assert!(matches!(
+            fortsetzen(&state, Uuid::new_v4(), None).await,
+            Err(SignalingError::NichtGefunden(_))
+        ));
+    }
+}
@@ -0,0 +1,135 @@
+//! Spielt einen Sitzungsmitschnitt gegen einen Server im Prozess ab
+//!
+//! Aufruf: `cargo run -p speakeasy-signaling --bin mitschnitt_wiedergabe -- <mitschnitt.ndjson> [optionen]`
+//!
+//! - `--tempo <faktor>`: Abstaende durch `faktor` teilen (`0` = ohne Pausen,
+//!   Standard `1` = Originaltempo)
+//! - `--erwartet <abbild.json>`: Zustandsabbild am Ende mit dieser Datei vergleichen
+//! - `--abbild <abbild.json>`: Zustandsabbild am Ende in diese Datei schreiben
+//!
+//! Endet mit Fehlercode bei einem Panic in einem Handler oder einer
+//! Abweichung vom erwarteten Abbild. Fehlerantworten des Servers werden nur
+//! aufgelistet.
+
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use speakeasy_signaling::mitschnitt::Mitschnitt;
+use speakeasy_signaling::wiedergabe::{pruefstand, wiedergeben, Tempo, Zustandsabbild};
+
+struct Optionen {
+    mitschnitt: PathBuf,
+    tempo: Tempo,
+    erwartet: Option<PathBuf>,
+    abbild: Option<PathBuf>,
+}
+
+fn optionen_lesen() -> Result<Optionen, String> {
+    let mut argumente = std::env::args().skip(1);
+    let mut mitschnitt = None;
+    let mut optionen = Optionen {
+        mitschnitt: PathBuf::new(),
+        tempo: Tempo::Original,
+        erwartet: None,
+        abbild: None,
+    };
+    while let Some(argument) = argumente.next() {
+        let mut wert = || {
+            argumente
+                .next()
+                .ok_or_else(|| format!("{argument} erwartet einen Wert"))
+        };
+        match argument.as_str() {
+            "--tempo" => {
+                let faktor: f64 = wert()?.parse().map_err(|e| format!("--tempo: {e}"))?;
+                optionen.tempo = if faktor <= 0.0 {
+                    Tempo::Sofort
+                } else if faktor == 1.0 {
+                    Tempo::Original
+                } else {
+                    Tempo::Faktor(faktor)
+                };
+            }
+            "--erwartet" => optionen.erwartet = Some(PathBuf::from(wert()?)),
+            "--abbild" => optionen.abbild = Some(PathBuf::from(wert()?)),
+            _ if mitschnitt.is_none() && !argument.starts_with("--") => {
+                mitschnitt = Some(PathBuf::from(&argument));
+            }
+            _ => return Err(format!("Unbekanntes Argument: {argument}")),
+        }
+    }
+    optionen.mitschnitt = mitschnitt.ok_or("Mitschnitt-Datei fehlt")?;
+    Ok(optionen)
+}
+
+#[tokio::main(flavor = "current_thread")]
+async fn main() -> ExitCode {
+    let optionen = match optionen_lesen() {
+        Ok(optionen) => optionen,
+        Err(e) => {
+            eprintln!("Fehler: {e}");
+            eprintln!(
+                "Aufruf: mitschnitt_wiedergabe <mitschnitt.ndjson> [--tempo <faktor>] \
+                 [--erwartet <abbild.json>] [--abbild <abbild.json>]"
+            );
+            return ExitCode::FAILURE;
+        }
+    };
+    match ausfuehren(&optionen).await {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::FAILURE,
+        Err(e) => {
+            eprintln!("Fehler: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+async fn ausfuehren(optionen: &Optionen) -> Result<bool, String> {
+    let text = std::fs::read_to_string(&optionen.mitschnitt)
+        .map_err(|e| format!("{}: {e}", optionen.mitschnitt.display()))?;
+    let mitschnitt = Mitschnitt::lesen(&text).map_err(|e| e.to_string())?;
+    let erwartet: Option<Zustandsabbild> = match &optionen.erwartet {
+        Some(pfad) => {
+            let inhalt =
+                std::fs::read_to_string(pfad).map_err(|e| format!("{}: {e}", pfad.display()))?;
+            Some(
+                serde_json::from_str(&inhalt)
+                    .map_err(|e| format!("{} ist ungueltig: {e}", pfad.display()))?,
+            )
+        }
+        None => None,
+    };
+
+    println!(
+        "{} Nachrichten (aufgezeichnet mit Protokollversion {})",
+        mitschnitt.eintraege.len(),
+        mitschnitt.kopf.protokoll_version
+    );
+    let state = pruefstand().await.map_err(|e| e.to_string())?;
+    let bericht = wiedergeben(&state, &mitschnitt, optionen.tempo)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    for fehler in &bericht.fehlerantworten {
+        println!(
+            "Eintrag {} (Verbindung {}): {:?} {}",
+            fehler.eintrag, fehler.verbindung, fehler.code, fehler.meldung
+        );
+    }
+    let abbild_json = serde_json::to_string_pretty(&bericht.abbild).map_err(|e| e.to_string())?;
+    match &optionen.abbild {
+        Some(pfad) => std::fs::write(pfad, abbild_json + "\n")
+            .map_err(|e| format!("{}: {e}", pfad.display()))?,
+        None => println!("{abbild_json}"),
+    }
+
+    let Some(erwartet) = erwartet else {
+        return Ok(true);
+    };
+    let abweichungen = erwartet.abweichungen(&bericht.abbild);
+    for abweichung in &abweichungen {
+        println!("Abweichung: {abweichung}");
+    }
+    Ok(abweichungen.is_empty())
+}
@@ -8,11 +8,19 @@
 //! - An einen Channel: `an_channel_senden`
 //! - An spezifische User: `an_user_senden`
 //! - An alle ausser einen: `an_alle_ausser_senden`
+//!
+//! ## Versionierte Presence-Ereignisse
+//! `zustand_senden` vergibt jedem Presence-Ereignis eine fortlaufende
+//! Zustandsversion und legt es serialisiert in einem begrenzten Replay-Ring
+//! ab. `diff_seit` liefert daraus die verpassten Ereignisse fuer
+//! `StateDiff`, ohne sie erneut zu serialisieren.
 
 use dashmap::DashMap;
 use speakeasy_core::types::{ChannelId, UserId};
-use speakeasy_protocol::control::ControlMessage;
-use std::sync::Arc;
+use speakeasy_protocol::control::{ControlMessage, StateDiffResponse};
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 use tokio::sync::mpsc;
 
 // ---------------------------------------------------------------------------
@@ -22,6 +30,24 @@ use tokio::sync::mpsc;
 /// Groesse der Send-Queue pro Client
 const SEND_QUEUE_GROESSE: usize = 64;
 
+/// Grenzen des Replay-Rings fuer Presence-Ereignisse
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReplayKonfig {
+    /// Maximale Anzahl gespeicherter Ereignisse (0 = kein Nachholen)
+    pub groesse: usize,
+    /// So lange bleibt ein Ereignis abrufbar
+    pub aufbewahrung: Duration,
+}
+
+impl Default for ReplayKonfig {
+    fn default() -> Self {
+        Self {
+            groesse: 1024,
+            aufbewahrung: Duration::from_secs(300),
+        }
+    }
+}
+
 // ---------------------------------------------------------------------------
 // ClientSender
 // ---------------------------------------------------------------------------
@@ -69,15 +95,68 @@ struct EventBroadcasterInner {
     clients: DashMap<UserId, ClientSender>,
     /// Channel-Mitgliedschaft: channel_id -> Vec<UserId>
     channel_members: DashMap<ChannelId, Vec<UserId>>,
+    /// Zustandsversion und zuletzt gesendete Presence-Ereignisse
+    replay: Mutex<ReplayRing>,
+}
+
+/// Serialisiertes Presence-Ereignis im Replay-Ring
+struct ReplayEintrag {
+    version: u64,
+    zeitpunkt: Instant,
+    json: Arc<str>,
+}
+
+struct ReplayRing {
+    konfig: ReplayKonfig,
+    /// Version des zuletzt gesendeten Ereignisses
+    version: u64,
+    /// Aufsteigend nach Version, ohne Luecken
+    eintraege: VecDeque<ReplayEintrag>,
+}
+
+impl ReplayRing {
+    fn neu(konfig: ReplayKonfig) -> Self {
+        // Startwert aus der Uhrzeit: Versionen eines frueheren Server-Laufs
+        // sind kleiner und fuehren nie zu einem falschen "aktuell"
+        let version = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis() as u64;
+        Self {
+            konfig,
+            version,
+            eintraege: VecDeque::new(),
+        }
+    }
+
+    /// Entfernt Eintraege jenseits von Groesse und Aufbewahrung
+    fn aufraeumen(&mut self, jetzt: Instant) {
+        while self.eintraege.len() > self.konfig.groesse {
+            self.eintraege.pop_front();
+        }
+        while self
+            .eintraege
+            .front()
+            .is_some_and(|e| jetzt.duration_since(e.zeitpunkt) > self.konfig.aufbewahrung)
+        {
+            self.eintraege.pop_front();
+        }
+    }
 }
 
 impl EventBroadcaster {
     /// Erstellt einen neuen EventBroadcaster
     pub fn neu() -> Self {
+        Self::mit_replay(ReplayKonfig::default())
+    }
+
+    /// Erstellt einen EventBroadcaster mit eigenen Replay-Grenzen
+    pub fn mit_replay(konfig: ReplayKonfig) -> Self {
         Self {
             inner: Arc::new(EventBroadcasterInner {
                 clients: DashMap::new(),
                 channel_members: DashMap::new(),
+                replay: Mutex::new(ReplayRing::neu(konfig)),
             }),
         }
     }
@@ -223,6 +302,104 @@ impl EventBroadcaster {
         gesendet
     }
 
+    /// Sendet ein Presence-Ereignis mit der naechsten Zustandsversion an alle
+    ///
+    /// `bauen` erhaelt die neue Version und erzeugt daraus die Nachricht.
+    /// Versionsvergabe, Ablage im Replay-Ring und Versand geschehen unter
+    /// einer Sperre, damit alle Clients die Ereignisse in Versionsreihenfolge
+    /// erhalten. Gibt die Anzahl der erfolgreichen Sendungen zurueck.
+    pub fn zustand_senden(&self, bauen: impl FnOnce(u64) -> ControlMessage) -> usize {
+        let (_ring, nachricht) = Self::versionieren(self.ring(), bauen);
+        self.an_alle_senden(nachricht)
+    }
+
+    /// Wie [`Self::zustand_senden`], aber nicht an den ausloesenden Client
+    ///
+    /// Der Ausloeser kennt die Aenderung bereits aus seiner Antwort. Im
+    /// Replay-Ring landet das Ereignis trotzdem, ein `StateDiff` liefert es
+    /// also auch ihm.
+    pub fn zustand_senden_ausser(
+        &self,
+        ausgeschlossen: &UserId,
+        bauen: impl FnOnce(u64) -> ControlMessage,
+    ) -> usize {
+        let (_ring, nachricht) = Self::versionieren(self.ring(), bauen);
+        self.an_alle_ausser_senden(ausgeschlossen, nachricht)
+    }
+
+    /// Vergibt die naechste Version und legt das Ereignis im Replay-Ring ab
+    ///
+    /// Gibt die Sperre mit zurueck: versendet wird noch unter ihr.
+    fn versionieren(
+        mut ring: std::sync::MutexGuard<'_, ReplayRing>,
+        bauen: impl FnOnce(u64) -> ControlMessage,
+    ) -> (std::sync::MutexGuard<'_, ReplayRing>, ControlMessage) {
+        ring.version += 1;
+        let version = ring.version;
+        let nachricht = bauen(version);
+
+        match nachricht.to_json() {
+            Ok(json) => {
+                ring.eintraege.push_back(ReplayEintrag {
+                    version,
+                    zeitpunkt: Instant::now(),
+                    json: json.into(),
+                });
+            }
+            Err(e) => {
+                // Ohne dieses Ereignis waere jeder Diff darueber falsch
+                tracing::warn!(version, fehler = %e, "Presence-Ereignis nicht serialisierbar");
+                ring.eintraege.clear();
+            }
+        }
+        ring.aufraeumen(Instant::now());
+        (ring, nachricht)
+    }
+
+    /// Aktuelle Zustandsversion
+    ///
+    /// Vor einem Abbild lesen: spaetere Ereignisse koennen dann schon im
+    /// Abbild enthalten sein, aber keines geht verloren.
+    pub fn zustand_version(&self) -> u64 {
+        self.ring().version
+    }
+
+    /// Presence-Ereignisse nach `seit` fuer eine `StateDiff`-Antwort
+    ///
+    /// Verlangt ein neues Abbild, wenn die Luecke nicht mehr vollstaendig im
+    /// Replay-Ring liegt oder `seit` nicht von diesem Server-Lauf stammt.
+    pub fn diff_seit(&self, seit: u64) -> StateDiffResponse {
+        let mut ring = self.ring();
+        ring.aufraeumen(Instant::now());
+        let aktuell = ring.version;
+
+        let vollstaendig = seit == aktuell
+            || (seit < aktuell
+                && ring
+                    .eintraege
+                    .front()
+                    .is_some_and(|e| e.version <= seit + 1));
+        let events = if vollstaendig {
+            ring.eintraege
+                .iter()
+                .filter(|e| e.version > seit)
+                .map(|e| e.json.to_string())
+                .collect()
+        } else {
+            Vec::new()
+        };
+
+        StateDiffResponse {
+            current_version: aktuell,
+            snapshot_required: !vollstaendig,
+            events,
+        }
+    }
+
+    fn ring(&self) -> std::sync::MutexGuard<'_, ReplayRing> {
+        self.inner.replay.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
     /// Gibt die Anzahl der registrierten Clients zurueck
     pub fn client_anzahl(&self) -> usize {
         self.inner.clients.len()
@@ -256,11 +433,27 @@ impl Default for EventBroadcaster {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use speakeasy_protocol::control::{ClientMovedEvent, ControlPayload};
 
     fn test_nachricht(id: u32) -> ControlMessage {
         ControlMessage::ping(id, 12345)
     }
 
+    fn verschoben(user_id: UserId, kanal: ChannelId) -> impl FnOnce(u64) -> ControlMessage {
+        move |state_version| {
+            ControlMessage::new(
+                0,
+                ControlPayload::ClientMoved(ClientMovedEvent {
+                    user_id,
+                    from_channel_id: None,
+                    to_channel_id: kanal,
+                    reason: None,
+                    state_version,
+                }),
+            )
+        }
+    }
+
     #[tokio::test]
     async fn client_registrieren_und_senden() {
         let broadcaster = EventBroadcaster::neu();
@@ -354,4 +547,68 @@ mod tests {
         assert!(!broadcaster.ist_registriert(&uid));
         assert_eq!(broadcaster.user_ids_in_channel(&kanal).len(), 0);
     }
+
+    #[tokio::test]
+    async fn diff_liefert_verpasste_ereignisse() {
+        let broadcaster = EventBroadcaster::neu();
+        let uid = UserId::new();
+        let mut rx = broadcaster.client_registrieren(uid);
+        let start = broadcaster.zustand_version();
+
+        let kanaele: Vec<ChannelId> = (0..3).map(|_| ChannelId::new()).collect();
+        for kanal in &kanaele {
+            broadcaster.zustand_senden(verschoben(uid, *kanal));
+        }
+        assert_eq!(broadcaster.zustand_version(), start + 3);
+
+        // Nachgeholt wird exakt, was live gesendet wurde
+        let diff = broadcaster.diff_seit(start + 1);
+        assert!(!diff.snapshot_required);
+        assert_eq!(diff.current_version, start + 3);
+        let live: Vec<String> = (0..3)
+            .map(|_| rx.try_recv().unwrap().to_json().unwrap())
+            .collect();
+        assert_eq!(diff.events, live[1..]);
+
+        let aktuell = broadcaster.diff_seit(start + 3);
+        assert!(!aktuell.snapshot_required);
+        assert!(aktuell.events.is_empty());
+    }
+
+    #[test]
+    fn ueberlauf_erzwingt_abbild() {
+        let broadcaster = EventBroadcaster::mit_replay(ReplayKonfig {
+            groesse: 2,
+            ..Default::default()
+        });
+        let uid = UserId::new();
+        let start = broadcaster.zustand_version();
+        for _ in 0..3 {
+            broadcaster.zustand_senden(verschoben(uid, ChannelId::new()));
+        }
+
+        // Ereignis start+1 ist aus dem Ring gefallen
+        let diff = broadcaster.diff_seit(start);
+        assert!(diff.snapshot_required);
+        assert!(diff.events.is_empty());
+        assert_eq!(diff.current_version, start + 3);
+
+        assert_eq!(broadcaster.diff_seit(start + 1).events.len(), 2);
+    }
+
+    #[test]
+    fn abgelaufene_und_fremde_versionen_erzwingen_abbild() {
+        let broadcaster = EventBroadcaster::mit_replay(ReplayKonfig {
+            aufbewahrung: Duration::ZERO,
+            ..Default::default()
+        });
+        let start = broadcaster.zustand_version();
+        broadcaster.zustand_senden(verschoben(UserId::new(), ChannelId::new()));
+        std::thread::sleep(Duration::from_millis(5));
+        assert!(broadcaster.diff_seit(start).snapshot_required);
+
+        // Version aus einem anderen Server-Lauf
+        assert!(broadcaster.diff_seit(start + 100).snapshot_required);
+        assert!(broadcaster.diff_seit(0).snapshot_required);
+    }
 }
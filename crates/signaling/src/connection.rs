@@ -15,15 +15,41 @@
 //! - Server sendet alle `keepalive_sek` einen Ping
 //! - Client muss innerhalb von `verbindungs_timeout_sek` antworten
 //! - Bei Timeout wird die Verbindung getrennt
+//!
+//! ## Trennung durch den Server
+//! Entfernt der Server den Benutzer aus dem Broadcaster (Kick, Ban,
+//! Kontoloeschung), endet dessen Broadcast-Queue. Die Verbindung stellt die
+//! bis dahin eingereihten Nachrichten noch zu und schliesst dann. Beim
+//! Logout bleibt sie offen, da der Kontext dann keinen Benutzer mehr hat.
+//! Eskaliert die [`VerbindungsDrossel`], schliesst die Verbindung nach der
+//! letzten `RateLimited`-Antwort.
+//!
+//! ## Handshake und Frame-Groesse
+//! Weist der Server das `Hello` ab (andere Major-Version), trennt die
+//! Verbindung nach der Fehlerantwort. Frames ueber `max_frame_bytes`
+//! beantwortet sie mit einem Fehler und schliesst dann; der Rest des
+//! Datenstroms liesse sich ohnehin nicht mehr zuordnen.
+//!
+//! ## Sitzungsablauf
+//! Beim Keepalive-Ping prueft die Verbindung die Restlaufzeit ihrer Session
+//! und warnt einmal mit `SessionExpiryWarning`, sobald sie unter
+//! `sitzungswarnung_sek` faellt (siehe [`crate::sitzungsablauf`]).
+//!
+//! ## Mitschnitt
+//! Ist ein Sitzungsmitschnitt aktiv, wird jede eingehende Nachricht mit
+//! ihren Antworten nach der Verarbeitung aufgezeichnet (siehe
+//! [`crate::mitschnitt`]).
 
 use futures_util::{SinkExt, StreamExt};
+use speakeasy_core::types::UserId;
+use speakeasy_core::{FehlerCode, SpeakeasyError};
 use speakeasy_db::{
-    repository::UserRepository, BanRepository, ChannelRepository, ChatMessageRepository,
-    PermissionRepository, ServerGroupRepository,
+    repository::UserRepository, AuditLogRepository, BanRepository, ChannelRepository,
+    ChatMessageRepository, InviteRepository, PermissionRepository, ServerGroupRepository,
 };
 use speakeasy_protocol::{
-    control::{ControlMessage, ErrorCode},
-    wire::FrameCodec,
+    control::{ControlMessage, ControlPayload, ErrorCode},
+    wire::{FrameCodec, FrameZuGross},
 };
 use std::net::SocketAddr;
 use std::sync::Arc;
@@ -32,8 +58,12 @@ use tokio::net::TcpStream;
 use tokio::sync::mpsc;
 use tokio_util::codec::Framed;
 
+use crate::anfragelimit::VerbindungsAnfragen;
 use crate::dispatcher::{DispatcherContext, MessageDispatcher};
+use crate::drosselung::VerbindungsDrossel;
+use crate::mitschnitt::SitzungsMitschnitt;
 use crate::server_state::SignalingState;
+use crate::sitzungsablauf::AblaufWarnung;
 
 // ---------------------------------------------------------------------------
 // Verbindungszustand
@@ -74,7 +104,13 @@ where
 
 impl<U, P, B> ClientConnection<U, P, B>
 where
-    U: UserRepository + ServerGroupRepository + ChannelRepository + ChatMessageRepository + 'static,
+    U: UserRepository
+        + ServerGroupRepository
+        + ChannelRepository
+        + ChatMessageRepository
+        + InviteRepository
+        + AuditLogRepository
+        + 'static,
     P: PermissionRepository + 'static,
     B: BanRepository + 'static,
 {
@@ -99,11 +135,16 @@ where
         tracing::info!(peer = %peer_addr, "Neue Verbindung");
 
         // Framed-Stream mit FrameCodec einrichten
-        let mut framed = Framed::new(stream, FrameCodec::new());
+        let mut framed = Framed::new(
+            stream,
+            FrameCodec::mit_empfangslimit(self.state.config.max_frame_bytes),
+        );
 
         // Ausgehende Nachrichten-Queue (Broadcaster -> TCP)
         // Wird nach dem Login mit der Broadcaster-Queue des Users verknuepft
         let (sende_tx, mut sende_rx) = mpsc::channel::<ControlMessage>(64);
+        // Meldet das Ende einer Broadcast-Queue (Benutzer aus dem Broadcaster entfernt)
+        let (beendet_tx, mut beendet_rx) = mpsc::channel::<UserId>(4);
 
         // Dispatcher und Kontext initialisieren
         let (shutdown_watch_tx, _) = tokio::sync::watch::channel(false);
@@ -111,15 +152,21 @@ where
             peer_addr,
             session_token: None,
             user_id: None,
+            protokoll: None,
             shutdown_tx: shutdown_watch_tx,
+            zwischenmeldungen: Vec::new(),
+            anfragen: VerbindungsAnfragen::neu(),
+            drossel: VerbindungsDrossel::neu(self.state.config.drosselung),
         };
         let dispatcher = MessageDispatcher::neu(Arc::clone(&self.state));
+        let mut mitschnitt = SitzungsMitschnitt::neu(&self.state.mitschnitte, peer_addr);
 
         // Zeitpunkt des letzten empfangenen Frames
         let mut letzter_empfang = Instant::now();
         // Zeitpunkt des naechsten Ping
         let mut naechster_ping = Instant::now() + keepalive_intervall;
         let mut ping_request_id: u32 = 0;
+        let mut ablauf = AblaufWarnung::neu(self.state.config.sitzungswarnung_sek);
 
         loop {
             let jetzt = Instant::now();
@@ -155,17 +202,58 @@ where
                                 // Request ueber den Broadcaster registriert
                             }
 
-                            // Dispatch
-                            if let Some(antwort) = dispatcher.dispatch(nachricht, &mut ctx).await {
-                                if let Err(e) = framed.send(antwort).await {
+                            let hello = matches!(nachricht.payload, ControlPayload::Hello(_));
+                            // Dispatch; Zwischenmeldungen gehen vor der Antwort raus
+                            let eingang = mitschnitt.aktiv().then(|| nachricht.clone());
+                            let antwort = dispatcher.dispatch(nachricht, &mut ctx).await;
+                            let mut ausgehend = std::mem::take(&mut ctx.zwischenmeldungen);
+                            ausgehend.extend(antwort);
+                            if let Some(eingang) = eingang {
+                                mitschnitt.aufzeichnen(
+                                    &self.state.mitschnitte,
+                                    &eingang,
+                                    &ausgehend,
+                                    ctx.user_id,
+                                );
+                            }
+                            let inkompatibel = hello && ausgehend.iter().any(|m| {
+                                matches!(
+                                    &m.payload,
+                                    ControlPayload::Error(f)
+                                        if f.fehler_code() == FehlerCode::ProtokollVersion
+                                )
+                            });
+                            let mut gesendet = true;
+                            for nachricht in ausgehend {
+                                if let Err(e) = framed.send(nachricht).await {
                                     tracing::warn!(
                                         peer = %peer_addr,
                                         fehler = %e,
                                         "Senden fehlgeschlagen"
                                     );
+                                    gesendet = false;
                                     break;
                                 }
                             }
+                            if !gesendet {
+                                break;
+                            }
+                            // Hello abgelehnt: Fehler ist zugestellt, jetzt trennen
+                            if inkompatibel {
+                                tracing::info!(
+                                    peer = %peer_addr,
+                                    "Verbindung wegen inkompatibler Protokollversion getrennt"
+                                );
+                                break;
+                            }
+                            // Zu oft gedrosselt: Ablehnung ist zugestellt, jetzt trennen
+                            if ctx.drossel.eskaliert() {
+                                tracing::info!(
+                                    peer = %peer_addr,
+                                    "Verbindung wegen Anfrageflut getrennt"
+                                );
+                                break;
+                            }
 
                             // Nach erfolgreichem Login: Broadcaster-Queue abonnieren
                             if let Some(uid) = ctx.user_id {
@@ -174,12 +262,14 @@ where
                                         self.state.broadcaster.client_registrieren(uid);
                                     // Spawn separaten Lese-Task fuer Broadcast-Queue
                                     let sende_tx_clone = sende_tx.clone();
+                                    let beendet_tx = beendet_tx.clone();
                                     tokio::spawn(async move {
                                         while let Some(msg) = recv_queue.recv().await {
                                             if sende_tx_clone.send(msg).await.is_err() {
-                                                break;
+                                                return;
                                             }
                                         }
+                                        let _ = beendet_tx.send(uid).await;
                                     });
                                 }
                             }
@@ -190,6 +280,13 @@ where
                                 fehler = %e,
                                 "Frame-Lesefehler"
                             );
+                            if let Some(zu_gross) = FrameZuGross::aus_io(&e) {
+                                let fehler = ControlMessage::fehler(
+                                    0,
+                                    SpeakeasyError::UngueltigeNachricht(zu_gross.to_string()),
+                                );
+                                let _ = framed.send(fehler).await;
+                            }
                             break;
                         }
                         None => {
@@ -212,6 +309,20 @@ where
                     }
                 }
 
+                // Broadcast-Queue beendet: vom Server getrennt?
+                Some(uid) = beendet_rx.recv() => {
+                    if ctx.user_id == Some(uid) && !self.state.broadcaster.ist_registriert(&uid) {
+                        // Ausstehende Nachrichten (z.B. Kick-Grund) noch zustellen
+                        while let Ok(ausgehend) = sende_rx.try_recv() {
+                            if framed.send(ausgehend).await.is_err() {
+                                break;
+                            }
+                        }
+                        tracing::info!(peer = %peer_addr, user_id = %uid, "Vom Server getrennt");
+                        break;
+                    }
+                }
+
                 // Keepalive-Ping
                 _ = tokio::time::sleep(ping_verzoegerung) => {
                     if jetzt >= naechster_ping {
@@ -231,6 +342,30 @@ where
                             break;
                         }
                         naechster_ping = Instant::now() + keepalive_intervall;
+
+                        // Laeuft die Session bald ab? Client zum Verlaengern auffordern
+                        if let Some(token) = &ctx.session_token {
+                            let warnung = match self
+                                .state
+                                .auth_service
+                                .session_restlaufzeit(token)
+                                .await
+                            {
+                                Ok(rest) => ablauf.pruefen(rest),
+                                Err(_) => None,
+                            };
+                            if let Some(warnung) = warnung {
+                                tracing::debug!(peer = %peer_addr, "Session laeuft bald ab");
+                                if let Err(e) = framed.send(warnung).await {
+                                    tracing::warn!(
+                                        peer = %peer_addr,
+                                        fehler = %e,
+                                        "Ablaufwarnung-Senden fehlgeschlagen"
+                                    );
+                                    break;
+                                }
+                            }
+                        }
                     }
                 }
 
@@ -264,3 +399,90 @@ where
         tracing::info!(peer = %peer_addr, "Verbindungs-Task beendet");
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::server_state::SignalingConfig;
+    use crate::test_hilfen::TestAufbau;
+    use speakeasy_protocol::control::ProtokollVersion;
+    use tokio::io::AsyncWriteExt;
+    use tokio::net::TcpListener;
+
+    /// Startet eine Verbindung mit `config` und gibt den Client-Stream zurueck
+    async fn verbunden(
+        config: SignalingConfig,
+    ) -> (
+        TcpStream,
+        impl std::future::Future<Output = ()>,
+        tokio::sync::watch::Sender<bool>,
+    ) {
+        let state = TestAufbau::default().config(config).bauen().await;
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let client = TcpStream::connect(listener.local_addr().unwrap())
+            .await
+            .unwrap();
+        let (server, peer_addr) = listener.accept().await.unwrap();
+        let (shutdown_tx, shutdown_rx) = tokio::sync::watch::channel(false);
+        let verbindung = ClientConnection::neu(state, peer_addr).verarbeiten(server, shutdown_rx);
+        (client, verbindung, shutdown_tx)
+    }
+
+    #[tokio::test]
+    async fn zu_grosser_frame_wird_vor_dem_trennen_beantwortet() {
+        let (mut client, verbindung, _shutdown) = verbunden(SignalingConfig {
+            max_frame_bytes: 64,
+            ..Default::default()
+        })
+        .await;
+
+        let client_seite = async move {
+            let mut frame = 1000u32.to_be_bytes().to_vec();
+            frame.extend_from_slice(&[b'x'; 1000]);
+            client.write_all(&frame).await.unwrap();
+
+            let mut framed = Framed::new(client, FrameCodec::new());
+            let antwort = framed.next().await.unwrap().unwrap();
+            let ControlPayload::Error(fehler) = antwort.payload else {
+                panic!("Erwartet Error, erhalten: {:?}", antwort.payload);
+            };
+            assert_eq!(fehler.fehler_code(), FehlerCode::UngueltigeAnfrage);
+            assert!(fehler.message.contains("Maximum: 64 Bytes"));
+            assert!(
+                framed.next().await.is_none(),
+                "Verbindung muss danach enden"
+            );
+        };
+        tokio::join!(verbindung, client_seite);
+    }
+
+    #[tokio::test]
+    async fn inkompatibles_hello_trennt_die_verbindung() {
+        let (client, verbindung, _shutdown) = verbunden(SignalingConfig::default()).await;
+
+        let client_seite = async move {
+            let mut framed = Framed::new(client, FrameCodec::new());
+            let mut hello = speakeasy_protocol::handshake::hello("zukunft");
+            hello.protocol_version = ProtokollVersion {
+                major: ProtokollVersion::AKTUELL.major + 1,
+                minor: 0,
+            };
+            framed
+                .send(ControlMessage::new(1, ControlPayload::Hello(hello)))
+                .await
+                .unwrap();
+
+            let antwort = framed.next().await.unwrap().unwrap();
+            assert_eq!(antwort.request_id, 1);
+            let ControlPayload::Error(fehler) = antwort.payload else {
+                panic!("Erwartet Error, erhalten: {:?}", antwort.payload);
+            };
+            assert_eq!(fehler.fehler_code(), FehlerCode::ProtokollVersion);
+            assert!(
+                framed.next().await.is_none(),
+                "Verbindung muss danach enden"
+            );
+        };
+        tokio::join!(verbindung, client_seite);
+    }
+}
@@ -5,21 +5,42 @@
 //!
 //! ## Zustandspruefung
 //! Bestimmte Nachrichten sind nur in bestimmten Verbindungszustaenden erlaubt:
-//! - `Login` nur im `Connected`/`Authenticating`-Zustand
+//! - `Hello` immer (Versionsabgleich vor dem Login)
+//! - `Login` und `Register` nur im `Connected`/`Authenticating`-Zustand und,
+//!   mit `hello_erforderlich`, erst nach einem angenommenen `Hello`
 //! - Alle anderen nur im `Authenticated`/`InChannel`-Zustand
+//!
+//! ## Gleichzeitige Anfragen
+//! Jede Anfrage ausser Hello, Ping/Pong und `ClientActivity` belegt einen Platz im
+//! [`AnfrageBegrenzer`](crate::anfragelimit::AnfrageBegrenzer), bis ihre
+//! Arbeit fertig ist. Ohne freien Platz antwortet der Dispatcher sofort mit
+//! `RateLimited`.
+//!
+//! ## Drosselung
+//! Vorher verbraucht jede Nachricht (ausser Pong) ein Token aus dem Eimer
+//! ihrer Kategorie in der [`VerbindungsDrossel`]. Ein leerer Eimer ergibt
+//! `RateLimited` mit `retry_after_secs`; zu viele Ablehnungen markieren die
+//! Verbindung zum Trennen und koennen die IP kurzzeitig bannen.
 
-use speakeasy_core::types::UserId;
+use speakeasy_core::{types::UserId, SpeakeasyError};
 use speakeasy_db::{
-    repository::UserRepository, BanRepository, ChannelRepository, ChatMessageRepository,
+    models::NeuerAuditEintrag,
+    repository::UserRepository,
+    zeitlimit::{self, Zeitueberschreitung, Zugriffsart},
+    AuditLogRepository, BanRepository, ChannelRepository, ChatMessageRepository, InviteRepository,
     PermissionRepository, ServerGroupRepository,
 };
-use speakeasy_protocol::control::{ControlMessage, ControlPayload, ErrorCode};
+use speakeasy_protocol::control::{ControlMessage, ControlPayload, ErrorCode, ProtokollVersion};
+use speakeasy_protocol::handshake::HANDSHAKE_SEIT;
+use std::future::Future;
 use std::net::SocketAddr;
 use std::sync::Arc;
 
+use crate::anfragelimit::{AnfragePlatz, VerbindungsAnfragen};
+use crate::drosselung::{wiederholen_nach_sek, Kategorie, Urteil, VerbindungsDrossel};
 use crate::handlers::{
-    auth_handler, channel_handler, chat_handler, client_handler, permission_handler,
-    server_handler, voice_handler,
+    auth_handler, channel_handler, chat_handler, client_handler, datei_handler, e2e_handler,
+    konto_handler, permission_handler, server_handler, voice_handler,
 };
 use crate::server_state::SignalingState;
 
@@ -31,8 +52,17 @@ pub struct DispatcherContext {
     pub session_token: Option<String>,
     /// Authentifizierte User-ID (None wenn nicht authentifiziert)
     pub user_id: Option<UserId>,
+    /// Protokollversion aus dem angenommenen `Hello` (None = noch keines)
+    pub protokoll: Option<ProtokollVersion>,
     /// Shutdown-Sender fuer Server-Stop-Kommando
     pub shutdown_tx: tokio::sync::watch::Sender<bool>,
+    /// Zwischenmeldungen zur laufenden Anfrage (z.B. `ChatHistoryChunk`),
+    /// die vor der Antwort gesendet werden
+    pub zwischenmeldungen: Vec<ControlMessage>,
+    /// Laufende Anfragen dieser Verbindung
+    pub anfragen: VerbindungsAnfragen,
+    /// Token-Eimer dieser Verbindung
+    pub drossel: VerbindungsDrossel,
 }
 
 /// Zentraler Message-Dispatcher
@@ -50,7 +80,13 @@ where
 
 impl<U, P, B> MessageDispatcher<U, P, B>
 where
-    U: UserRepository + ServerGroupRepository + ChannelRepository + ChatMessageRepository + 'static,
+    U: UserRepository
+        + ServerGroupRepository
+        + ChannelRepository
+        + ChatMessageRepository
+        + InviteRepository
+        + AuditLogRepository
+        + 'static,
     P: PermissionRepository + 'static,
     B: BanRepository + 'static,
 {
@@ -70,10 +106,45 @@ where
     ) -> Option<ControlMessage> {
         let request_id = message.request_id;
 
+        if let Some(ablehnung) = self.drosseln(&message.payload, request_id, ctx).await {
+            return Some(ablehnung);
+        }
+
+        let platz = if ist_leichtgewichtig(&message.payload) {
+            None
+        } else {
+            let datenbank = ist_datenbanklastig(&message.payload);
+            let anfragen = &self.state.anfragen;
+            match anfragen
+                .belegen(&ctx.anfragen, ctx.user_id, datenbank)
+                .await
+            {
+                Ok(platz) => Some(platz),
+                Err(grund) => {
+                    tracing::debug!(
+                        peer = %ctx.peer_addr,
+                        request_id,
+                        grund = ?grund,
+                        "Anfrage wegen Gleichzeitigkeitsgrenze abgelehnt"
+                    );
+                    return Some(anfragen.ablehnung_antwort(request_id, grund));
+                }
+            }
+        };
+
         match message.payload {
             // -------------------------------------------------------------------
             // Auth-Nachrichten (immer erlaubt)
             // -------------------------------------------------------------------
+            ControlPayload::Hello(req) => {
+                let version = req.protocol_version;
+                let antwort = auth_handler::handle_hello(req, request_id);
+                if matches!(antwort.payload, ControlPayload::Welcome(_)) {
+                    ctx.protokoll = Some(version);
+                }
+                Some(antwort)
+            }
+
             ControlPayload::Login(req) => {
                 // Login nur wenn noch nicht authentifiziert
                 if ctx.user_id.is_some() {
@@ -83,15 +154,25 @@ where
                         "Bereits angemeldet",
                     ));
                 }
+                if let Some(ablehnung) = self.ohne_hello(request_id, ctx) {
+                    return Some(ablehnung);
+                }
 
                 let peer_ip = ctx.peer_addr.ip().to_string();
-                let antwort =
-                    auth_handler::handle_login(req, request_id, &peer_ip, &self.state).await;
+                let state = Arc::clone(&self.state);
+                let arbeit = async move {
+                    auth_handler::handle_login(req, request_id, &peer_ip, &state).await
+                };
+                let antwort = match self.begrenzt(Zugriffsart::Schreiben, platz, arbeit).await {
+                    Ok(antwort) => antwort,
+                    Err(e) => return Some(zeitueberschreitung_antwort(request_id, e)),
+                };
 
                 // Bei Erfolg: Session-Token und User-ID speichern
                 if let ControlPayload::LoginResponse(ref resp) = antwort.payload {
                     ctx.session_token = Some(resp.session_token.clone());
                     ctx.user_id = Some(resp.user_id);
+                    self.state.aktivitaet.melden(resp.user_id);
                     tracing::debug!(
                         user_id = %resp.user_id,
                         "Verbindung authentifiziert"
@@ -101,6 +182,29 @@ where
                 Some(antwort)
             }
 
+            ControlPayload::Register(req) => {
+                if ctx.user_id.is_some() {
+                    return Some(ControlMessage::error(
+                        request_id,
+                        ErrorCode::AlreadyLoggedIn,
+                        "Bereits angemeldet",
+                    ));
+                }
+                if let Some(ablehnung) = self.ohne_hello(request_id, ctx) {
+                    return Some(ablehnung);
+                }
+
+                let peer_ip = ctx.peer_addr.ip().to_string();
+                let state = Arc::clone(&self.state);
+                let arbeit = async move {
+                    auth_handler::handle_register(req, request_id, &peer_ip, &state).await
+                };
+                match self.begrenzt(Zugriffsart::Schreiben, platz, arbeit).await {
+                    Ok(antwort) => Some(antwort),
+                    Err(e) => Some(zeitueberschreitung_antwort(request_id, e)),
+                }
+            }
+
             ControlPayload::Logout(_req) => {
                 let token = match &ctx.session_token {
                     Some(t) => t.clone(),
@@ -118,7 +222,13 @@ where
                     self.client_cleanup(&uid).await;
                 }
 
-                let antwort = auth_handler::handle_logout(&token, request_id, &self.state).await;
+                let state = Arc::clone(&self.state);
+                let arbeit =
+                    async move { auth_handler::handle_logout(&token, request_id, &state).await };
+                let antwort = self
+                    .begrenzt(Zugriffsart::Schreiben, platz, arbeit)
+                    .await
+                    .unwrap_or_else(|e| zeitueberschreitung_antwort(request_id, e));
 
                 ctx.session_token = None;
                 ctx.user_id = None;
@@ -126,6 +236,53 @@ where
                 Some(antwort)
             }
 
+            ControlPayload::SessionRenew => {
+                let Some(token) = ctx.session_token.clone() else {
+                    return Some(ControlMessage::error(
+                        request_id,
+                        ErrorCode::SessionExpired,
+                        "Nicht angemeldet",
+                    ));
+                };
+
+                let state = Arc::clone(&self.state);
+                let arbeit = async move {
+                    auth_handler::handle_session_renew(&token, request_id, &state).await
+                };
+                match self.begrenzt(Zugriffsart::Schreiben, platz, arbeit).await {
+                    Ok(antwort) => Some(antwort),
+                    Err(e) => Some(zeitueberschreitung_antwort(request_id, e)),
+                }
+            }
+
+            ControlPayload::AccountDelete(req) => {
+                let Some(user_id) = ctx.user_id else {
+                    return Some(ControlMessage::error(
+                        request_id,
+                        ErrorCode::SessionExpired,
+                        "Nicht angemeldet",
+                    ));
+                };
+
+                let state = Arc::clone(&self.state);
+                let arbeit = async move {
+                    konto_handler::handle_account_delete(req, request_id, user_id, &state).await
+                };
+                let antwort = match self.begrenzt(Zugriffsart::Schreiben, platz, arbeit).await {
+                    Ok(antwort) => antwort,
+                    Err(e) => return Some(zeitueberschreitung_antwort(request_id, e)),
+                };
+
+                // Das Konto existiert nicht mehr: Verbindung wie beim Logout abmelden
+                if matches!(antwort.payload, ControlPayload::AccountDeleteResponse(_)) {
+                    self.client_cleanup(&user_id).await;
+                    ctx.session_token = None;
+                    ctx.user_id = None;
+                }
+
+                Some(antwort)
+            }
+
             // -------------------------------------------------------------------
             // Keepalive
             // -------------------------------------------------------------------
@@ -147,6 +304,56 @@ where
                 None
             }
 
+            // -------------------------------------------------------------------
+            // Chat-Verlauf in Teilstuecken: mehrere Nachrichten pro Anfrage
+            // -------------------------------------------------------------------
+            ControlPayload::ChatHistory(req) if req.chunked => {
+                if ctx.user_id.is_none() {
+                    return Some(ControlMessage::error(
+                        request_id,
+                        ErrorCode::SessionExpired,
+                        "Nicht authentifiziert – bitte zuerst anmelden",
+                    ));
+                }
+
+                let state = Arc::clone(&self.state);
+                let arbeit = async move {
+                    chat_handler::handle_chat_history_chunked(req, request_id, &state).await
+                };
+                let mut folge = match self.begrenzt(Zugriffsart::Lesen, platz, arbeit).await {
+                    Ok(folge) => folge,
+                    Err(e) => return Some(zeitueberschreitung_antwort(request_id, e)),
+                };
+                let antwort = folge.pop();
+                ctx.zwischenmeldungen.extend(folge);
+                antwort
+            }
+
+            // -------------------------------------------------------------------
+            // Voice-Statistik: eine neue Ziel-Bitrate geht vor der Antwort raus
+            // -------------------------------------------------------------------
+            ControlPayload::VoiceStats(req) => {
+                let Some(user_id) = ctx.user_id else {
+                    return Some(ControlMessage::error(
+                        request_id,
+                        ErrorCode::SessionExpired,
+                        "Nicht authentifiziert – bitte zuerst anmelden",
+                    ));
+                };
+
+                let state = Arc::clone(&self.state);
+                let arbeit = async move {
+                    voice_handler::handle_voice_stats(req, request_id, user_id, &state).await
+                };
+                let mut folge = match self.begrenzt(Zugriffsart::Lesen, platz, arbeit).await {
+                    Ok(folge) => folge,
+                    Err(e) => return Some(zeitueberschreitung_antwort(request_id, e)),
+                };
+                let antwort = folge.pop();
+                ctx.zwischenmeldungen.extend(folge);
+                antwort
+            }
+
             // -------------------------------------------------------------------
             // Authentifizierung erfordernde Nachrichten
             // -------------------------------------------------------------------
@@ -162,132 +369,211 @@ where
                     }
                 };
 
-                self.dispatch_authenticated(payload, request_id, user_id, ctx)
-                    .await
+                let art = zugriffsart(&payload);
+                let arbeit = Self::dispatch_authenticated(
+                    Arc::clone(&self.state),
+                    payload,
+                    request_id,
+                    user_id,
+                    ctx.peer_addr,
+                    ctx.shutdown_tx.clone(),
+                );
+                match self.begrenzt(art, platz, arbeit).await {
+                    Ok(antwort) => antwort,
+                    Err(e) => Some(zeitueberschreitung_antwort(request_id, e)),
+                }
             }
         }
     }
 
     /// Routet Nachrichten die eine Authentifizierung erfordern
+    ///
+    /// Besitzt alle Eingaben, damit schreibende Nachrichten als eigener Task
+    /// zu Ende laufen koennen (siehe `begrenzt`).
     async fn dispatch_authenticated(
-        &self,
+        state: Arc<SignalingState<U, P, B>>,
         payload: ControlPayload,
         request_id: u32,
         user_id: UserId,
-        ctx: &mut DispatcherContext,
+        peer_addr: SocketAddr,
+        shutdown_tx: tokio::sync::watch::Sender<bool>,
     ) -> Option<ControlMessage> {
         match payload {
             // -------------------------------------------------------------------
             // Channel-Nachrichten
             // -------------------------------------------------------------------
-            ControlPayload::ChannelList => {
-                Some(channel_handler::handle_channel_list(request_id, &self.state).await)
+            ControlPayload::ChannelList(req) => {
+                Some(channel_handler::handle_channel_list(req, request_id, user_id, &state).await)
             }
 
-            ControlPayload::ChannelJoin(req) => Some(
-                channel_handler::handle_channel_join(req, request_id, user_id, &self.state).await,
+            ControlPayload::ChannelTreeExpand(req) => {
+                Some(channel_handler::handle_channel_tree_expand(req, request_id, &state).await)
+            }
+
+            ControlPayload::ChannelMembers(req) => Some(channel_handler::handle_channel_members(
+                req, request_id, &state,
+            )),
+
+            ControlPayload::ChannelJoin(req) => {
+                state.aktivitaet.melden(user_id);
+                Some(channel_handler::handle_channel_join(req, request_id, user_id, &state).await)
+            }
+
+            ControlPayload::ChannelLeave(req) => {
+                Some(channel_handler::handle_channel_leave(req, request_id, user_id, &state).await)
+            }
+
+            ControlPayload::ChannelCreate(req) => {
+                Some(channel_handler::handle_channel_create(req, request_id, user_id, &state).await)
+            }
+
+            ControlPayload::ChannelEdit(req) => {
+                Some(channel_handler::handle_channel_edit(req, request_id, user_id, &state).await)
+            }
+
+            ControlPayload::ChannelDelete(req) => {
+                Some(channel_handler::handle_channel_delete(req, request_id, user_id, &state).await)
+            }
+
+            ControlPayload::ChannelEmergencyMute(req) => Some(
+                channel_handler::handle_channel_emergency_mute(req, request_id, user_id, &state)
+                    .await,
             ),
 
-            ControlPayload::ChannelLeave(req) => Some(
-                channel_handler::handle_channel_leave(req, request_id, user_id, &self.state).await,
+            ControlPayload::ChannelInvite(req) => {
+                Some(channel_handler::handle_channel_invite(req, request_id, user_id, &state).await)
+            }
+
+            ControlPayload::ChannelInviteAnswer(req) => Some(
+                channel_handler::handle_channel_invite_answer(req, request_id, user_id, &state)
+                    .await,
             ),
 
-            ControlPayload::ChannelCreate(req) => Some(
-                channel_handler::handle_channel_create(req, request_id, user_id, &self.state).await,
+            ControlPayload::ChannelKnock(req) => {
+                Some(channel_handler::handle_channel_knock(req, request_id, user_id, &state).await)
+            }
+
+            ControlPayload::ChannelKnockAnswer(req) => Some(
+                channel_handler::handle_channel_knock_answer(req, request_id, user_id, &state)
+                    .await,
             ),
 
-            ControlPayload::ChannelEdit(req) => Some(
-                channel_handler::handle_channel_edit(req, request_id, user_id, &self.state).await,
+            ControlPayload::SoundboardPlay(req) => Some(
+                channel_handler::handle_soundboard_play(req, request_id, user_id, &state).await,
             ),
 
-            ControlPayload::ChannelDelete(req) => Some(
-                channel_handler::handle_channel_delete(req, request_id, user_id, &self.state).await,
+            ControlPayload::SoundboardStop(req) => Some(
+                channel_handler::handle_soundboard_stop(req, request_id, user_id, &state).await,
             ),
 
             // -------------------------------------------------------------------
             // Account-Management
             // -------------------------------------------------------------------
-            ControlPayload::PasswordChange(req) => Some(
-                auth_handler::handle_password_change(req, request_id, user_id, &self.state).await,
-            ),
+            ControlPayload::PasswordChange(req) => {
+                Some(auth_handler::handle_password_change(req, request_id, user_id, &state).await)
+            }
 
-            ControlPayload::NicknameChange(req) => Some(
-                auth_handler::handle_nickname_change(req, request_id, user_id, &self.state).await,
-            ),
+            ControlPayload::NicknameChange(req) => {
+                Some(auth_handler::handle_nickname_change(req, request_id, user_id, &state).await)
+            }
 
-            ControlPayload::SetAway(req) => Some(
-                auth_handler::handle_set_away(req, request_id, user_id, &self.state).await,
-            ),
+            ControlPayload::SetAway(req) => {
+                Some(auth_handler::handle_set_away(req, request_id, user_id, &state).await)
+            }
+
+            ControlPayload::AccountDataExport => {
+                Some(konto_handler::handle_account_data_export(request_id, user_id, &state).await)
+            }
 
             // -------------------------------------------------------------------
             // Client-Nachrichten
             // -------------------------------------------------------------------
             ControlPayload::ClientList => {
-                Some(client_handler::handle_client_list(request_id, &self.state).await)
+                Some(client_handler::handle_client_list(request_id, &state).await)
             }
 
-            ControlPayload::ClientKick(req) => Some(
-                client_handler::handle_client_kick(req, request_id, user_id, &self.state).await,
-            ),
+            ControlPayload::StateDiff(req) => {
+                Some(client_handler::handle_state_diff(req, request_id, &state).await)
+            }
+
+            ControlPayload::ClientKick(req) => {
+                Some(client_handler::handle_client_kick(req, request_id, user_id, &state).await)
+            }
 
             ControlPayload::ClientBan(req) => {
-                Some(client_handler::handle_client_ban(req, request_id, user_id, &self.state).await)
+                Some(client_handler::handle_client_ban(req, request_id, user_id, &state).await)
             }
 
-            ControlPayload::ClientMove(req) => Some(
-                client_handler::handle_client_move(req, request_id, user_id, &self.state).await,
-            ),
+            ControlPayload::ClientMove(req) => {
+                Some(client_handler::handle_client_move(req, request_id, user_id, &state).await)
+            }
 
-            ControlPayload::ClientPoke(req) => Some(
-                client_handler::handle_client_poke(req, request_id, user_id, &self.state).await,
+            ControlPayload::ClientsMoveAll(req) => Some(
+                client_handler::handle_clients_move_all(req, request_id, user_id, &state).await,
             ),
 
-            ControlPayload::ClientUpdate(req) => Some(
-                client_handler::handle_client_update(req, request_id, user_id, &self.state).await,
-            ),
+            ControlPayload::ClientPoke(req) => {
+                Some(client_handler::handle_client_poke(req, request_id, user_id, &state).await)
+            }
+
+            ControlPayload::ClientUpdate(req) => {
+                Some(client_handler::handle_client_update(req, request_id, user_id, &state).await)
+            }
+
+            ControlPayload::ResolveIds(req) => {
+                Some(client_handler::handle_resolve_ids(req, request_id, &state).await)
+            }
+
+            ControlPayload::ClientActivity => {
+                // Leichtgewichtiger Ping ohne Antwort
+                state.aktivitaet.melden(user_id);
+                None
+            }
 
             // -------------------------------------------------------------------
             // Server-Nachrichten
             // -------------------------------------------------------------------
             ControlPayload::ServerInfo => {
-                Some(server_handler::handle_server_info(request_id, &self.state).await)
+                Some(server_handler::handle_server_info(request_id, &state).await)
             }
 
-            ControlPayload::ServerEdit(req) => Some(
-                server_handler::handle_server_edit(req, request_id, user_id, &self.state).await,
-            ),
+            ControlPayload::ServerEdit(req) => {
+                Some(server_handler::handle_server_edit(req, request_id, user_id, &state).await)
+            }
 
             ControlPayload::ServerStop(req) => Some(
-                server_handler::handle_server_stop(
-                    req,
-                    request_id,
-                    user_id,
-                    &self.state,
-                    &ctx.shutdown_tx,
-                )
-                .await,
+                server_handler::handle_server_stop(req, request_id, user_id, &state, &shutdown_tx)
+                    .await,
+            ),
+
+            ControlPayload::CancelPendingDisconnect(req) => Some(
+                server_handler::handle_cancel_pending_disconnect(req, request_id, user_id, &state)
+                    .await,
             ),
 
+            ControlPayload::SessionCapture(req) => {
+                Some(server_handler::handle_session_capture(req, request_id, user_id, &state).await)
+            }
+
             // -------------------------------------------------------------------
             // Permission-Nachrichten
             // -------------------------------------------------------------------
             ControlPayload::PermissionList { target } => Some(
-                permission_handler::handle_permission_list(
-                    target,
-                    request_id,
-                    user_id,
-                    &self.state,
-                )
-                .await,
+                permission_handler::handle_permission_list(target, request_id, user_id, &state)
+                    .await,
             ),
 
             ControlPayload::PermissionAdd(req) => Some(
-                permission_handler::handle_permission_add(req, request_id, user_id, &self.state)
-                    .await,
+                permission_handler::handle_permission_add(req, request_id, user_id, &state).await,
             ),
 
             ControlPayload::PermissionRemove(req) => Some(
-                permission_handler::handle_permission_remove(req, request_id, user_id, &self.state)
+                permission_handler::handle_permission_remove(req, request_id, user_id, &state)
+                    .await,
+            ),
+
+            ControlPayload::EffectivePermissions(req) => Some(
+                permission_handler::handle_effective_permissions(req, request_id, user_id, &state)
                     .await,
             ),
 
@@ -295,58 +581,110 @@ where
             // Voice-Setup-Nachrichten
             // -------------------------------------------------------------------
             ControlPayload::VoiceInit(req) => Some(
-                voice_handler::handle_voice_init(
-                    req,
-                    request_id,
-                    user_id,
-                    ctx.peer_addr,
-                    &self.state,
-                )
-                .await,
+                voice_handler::handle_voice_init(req, request_id, user_id, peer_addr, &state).await,
             ),
 
-            ControlPayload::VoiceDisconnect(req) => Some(
-                voice_handler::handle_voice_disconnect(req, request_id, user_id, &self.state).await,
-            ),
+            ControlPayload::VoiceDisconnect(req) => {
+                Some(voice_handler::handle_voice_disconnect(req, request_id, user_id, &state).await)
+            }
+
+            // VoiceStats wird oben behandelt (Folge mit Bitrate-Meldung)
+            ControlPayload::VoiceStats(_) => None,
+
+            ControlPayload::E2EKey(req) => {
+                e2e_handler::handle_e2e_key(req, request_id, user_id, &state)
+            }
 
             // -------------------------------------------------------------------
             // Chat-Nachrichten
             // -------------------------------------------------------------------
             ControlPayload::ChatSend(req) => {
-                Some(chat_handler::handle_chat_send(req, request_id, user_id, &self.state).await)
+                state.aktivitaet.melden(user_id);
+                Some(chat_handler::handle_chat_send(req, request_id, user_id, &state).await)
             }
 
             ControlPayload::ChatEdit(req) => {
-                Some(chat_handler::handle_chat_edit(req, request_id, user_id, &self.state).await)
+                Some(chat_handler::handle_chat_edit(req, request_id, user_id, &state).await)
             }
 
             ControlPayload::ChatDelete(req) => {
-                Some(chat_handler::handle_chat_delete(req, request_id, user_id, &self.state).await)
+                Some(chat_handler::handle_chat_delete(req, request_id, user_id, &state).await)
             }
 
             ControlPayload::ChatHistory(req) => {
-                Some(chat_handler::handle_chat_history(req, request_id, &self.state).await)
+                Some(chat_handler::handle_chat_history(req, request_id, &state).await)
+            }
+
+            ControlPayload::ChatSearch(req) => {
+                Some(chat_handler::handle_chat_search(req, request_id, user_id, &state).await)
             }
 
             // -------------------------------------------------------------------
             // Unbekannte / unerwartete Nachrichten
             // -------------------------------------------------------------------
-            ControlPayload::LoginResponse(_)
+            ControlPayload::Welcome(_)
+            | ControlPayload::LoginResponse(_)
+            | ControlPayload::RegisterResponse(_)
             | ControlPayload::LogoutResponse(_)
+            | ControlPayload::SessionExpiryWarning(_)
+            | ControlPayload::SessionRenewResponse(_)
             | ControlPayload::PasswordChangeResponse(_)
             | ControlPayload::NicknameChangeResponse(_)
             | ControlPayload::SetAwayResponse(_)
+            | ControlPayload::AccountDataExportResponse(_)
+            | ControlPayload::AccountExportReady(_)
+            | ControlPayload::AccountDeleteResponse(_)
             | ControlPayload::ChannelListResponse(_)
             | ControlPayload::ChannelJoinResponse(_)
+            | ControlPayload::ChannelMembersResponse(_)
             | ControlPayload::ChannelCreateResponse(_)
+            | ControlPayload::ChannelEdited(_)
+            | ControlPayload::ChannelTreeChanged(_)
+            | ControlPayload::ChannelEmergencyMuteEvent(_)
+            | ControlPayload::ChannelInviteResponse(_)
+            | ControlPayload::ChannelInviteReceived(_)
+            | ControlPayload::ChannelInviteResult(_)
+            | ControlPayload::ChannelKnockResponse(_)
+            | ControlPayload::ChannelKnockReceived(_)
+            | ControlPayload::ChannelKnockResult(_)
+            | ControlPayload::SoundboardPlayback(_)
+            | ControlPayload::MotdChanged(_)
+            | ControlPayload::ServerAnnouncement(_)
             | ControlPayload::ClientListResponse(_)
+            | ControlPayload::ClientMoved(_)
+            | ControlPayload::ClientsMoveAllResponse(_)
+            | ControlPayload::ClientsMoved(_)
+            | ControlPayload::ClientConnected(_)
+            | ControlPayload::ClientDisconnected(_)
+            | ControlPayload::ClientJoinedChannel(_)
+            | ControlPayload::ClientLeftChannel(_)
+            | ControlPayload::ClientVoiceUpdated(_)
+            | ControlPayload::ClientSpeaking(_)
+            | ControlPayload::ClientUpdated(_)
+            | ControlPayload::ResolveIdsResponse(_)
+            | ControlPayload::StateDiffResponse(_)
             | ControlPayload::ServerInfoResponse(_)
+            | ControlPayload::PendingDisconnectNotice(_)
+            | ControlPayload::PendingDisconnectCancelled(_)
+            | ControlPayload::SessionCaptureResponse(_)
             | ControlPayload::PermissionListResponse(_)
+            | ControlPayload::EffectivePermissionsResponse(_)
             | ControlPayload::FileListResponse(_)
             | ControlPayload::FileUploadResponse(_)
+            | ControlPayload::FileDownloadResponse(_)
             | ControlPayload::ChatSendResponse(_)
             | ControlPayload::ChatHistoryResponse(_)
+            | ControlPayload::ChatHistoryChunk(_)
+            | ControlPayload::ChatHistoryComplete(_)
+            | ControlPayload::ChatSearchResponse(_)
+            | ControlPayload::ChatMessage(_)
+            | ControlPayload::ChatEdited(_)
+            | ControlPayload::ChatDeleted(_)
+            | ControlPayload::ChatBulkDeleted(_)
             | ControlPayload::VoiceReady(_)
+            | ControlPayload::VoiceStatsResponse(_)
+            | ControlPayload::VoiceQualityUpdate(_)
+            | ControlPayload::E2EKeyRotationRequired(_)
             | ControlPayload::Error(_) => {
                 tracing::warn!(
                     request_id,
@@ -359,20 +697,27 @@ where
                 ))
             }
 
-            // File-Nachrichten (noch nicht implementiert)
-            ControlPayload::FileList { .. }
-            | ControlPayload::FileUpload(_)
-            | ControlPayload::FileDelete(_) => Some(ControlMessage::error(
-                request_id,
-                ErrorCode::InvalidRequest,
-                "File-Service noch nicht implementiert",
-            )),
+            ControlPayload::FileDownload(req) => {
+                Some(datei_handler::handle_file_download(req, request_id, user_id, &state).await)
+            }
+            ControlPayload::FileUpload(req) => {
+                Some(datei_handler::handle_file_upload(req, request_id, user_id, &state).await)
+            }
 
-            // Ping/Pong werden oben bereits behandelt
-            ControlPayload::Ping(_) | ControlPayload::Pong(_) => None,
+            // Uebrige File-Nachrichten (noch nicht implementiert)
+            ControlPayload::FileList { .. } | ControlPayload::FileDelete(_) => {
+                Some(ControlMessage::error(
+                    request_id,
+                    ErrorCode::InvalidRequest,
+                    "File-Service noch nicht implementiert",
+                ))
+            }
+
+            // Hello und Ping/Pong werden oben bereits behandelt
+            ControlPayload::Hello(_) | ControlPayload::Ping(_) | ControlPayload::Pong(_) => None,
 
             // Login/Logout im authentifizierten Zustand – Fehlermeldung
-            ControlPayload::Login(_) => Some(ControlMessage::error(
+            ControlPayload::Login(_) | ControlPayload::Register(_) => Some(ControlMessage::error(
                 request_id,
                 ErrorCode::AlreadyLoggedIn,
                 "Bereits angemeldet",
@@ -382,16 +727,1421 @@ where
                 ErrorCode::InvalidRequest,
                 "Logout muss ueber den normalen Pfad erfolgen",
             )),
+            ControlPayload::AccountDelete(_) => Some(ControlMessage::error(
+                request_id,
+                ErrorCode::InvalidRequest,
+                "Kontoloeschung muss ueber den normalen Pfad erfolgen",
+            )),
+            ControlPayload::SessionRenew => Some(ControlMessage::error(
+                request_id,
+                ErrorCode::InvalidRequest,
+                "Verlaengerung muss ueber den normalen Pfad erfolgen",
+            )),
+        }
+    }
+
+    /// Lehnt `Login`/`Register` vor dem `Hello` ab, wenn der Server es verlangt
+    fn ohne_hello(&self, request_id: u32, ctx: &DispatcherContext) -> Option<ControlMessage> {
+        if !self.state.config.hello_erforderlich || ctx.protokoll.is_some() {
+            return None;
+        }
+        Some(ControlMessage::fehler(
+            request_id,
+            SpeakeasyError::UngueltigeNachricht(format!(
+                "Hello vor dem Login erwartet; bitte den Client aktualisieren \
+                 (Protokoll >= {HANDSHAKE_SEIT})"
+            )),
+        ))
+    }
+
+    /// Verbucht die Nachricht in der Drossel der Verbindung
+    ///
+    /// Gibt die `RateLimited`-Antwort zurueck, wenn der Eimer leer ist. Bei
+    /// Eskalation wird die IP je nach Konfiguration gebannt; das Trennen
+    /// uebernimmt die Verbindung nach dem Senden der Antwort.
+    async fn drosseln(
+        &self,
+        payload: &ControlPayload,
+        request_id: u32,
+        ctx: &mut DispatcherContext,
+    ) -> Option<ControlMessage> {
+        let kategorie = Kategorie::von(payload)?;
+        let warten = match ctx.drossel.pruefen(kategorie) {
+            Urteil::Erlaubt => return None,
+            Urteil::Abgelehnt { warten } => {
+                tracing::debug!(
+                    peer = %ctx.peer_addr,
+                    request_id,
+                    kategorie = ?kategorie,
+                    "Anfrage gedrosselt"
+                );
+                warten
+            }
+            Urteil::Trennen { warten } => {
+                tracing::warn!(
+                    peer = %ctx.peer_addr,
+                    user_id = ?ctx.user_id,
+                    kategorie = ?kategorie,
+                    "Zu viele gedrosselte Anfragen – Verbindung wird getrennt"
+                );
+                if let Some(dauer) = ctx.drossel.limits().ip_sperre {
+                    self.automatisch_bannen(ctx, dauer).await;
+                }
+                warten
+            }
+        };
+        Some(ControlMessage::fehler_mit_kontext(
+            request_id,
+            "Zu viele Anfragen auf dieser Verbindung",
+            SpeakeasyError::RateLimit {
+                retry_after_secs: wiederholen_nach_sek(warten),
+            },
+        ))
+    }
+
+    /// Bannt die IP einer eskalierten Verbindung fuer `dauer`
+    async fn automatisch_bannen(&self, ctx: &DispatcherContext, dauer: std::time::Duration) {
+        let ip = ctx.peer_addr.ip().to_string();
+        let ban = match self
+            .state
+            .ban_service
+            .ip_bannen(None, &ip, "Automatisch: zu viele Anfragen", Some(dauer))
+            .await
+        {
+            Ok(ban) => ban,
+            Err(e) => {
+                tracing::warn!(ip = %ip, fehler = %e, "Automatischer IP-Ban fehlgeschlagen");
+                return;
+            }
+        };
+        self.state
+            .audit_protokollieren(NeuerAuditEintrag::neu(
+                None,
+                "ban.ip_automatisch",
+                Some("ban"),
+                Some(&ban.id.to_string()),
+                serde_json::json!({
+                    "ip": ip,
+                    "dauer_sek": dauer.as_secs(),
+                    "user_id": ctx.user_id.map(|u| u.to_string()),
+                }),
+            ))
+            .await;
+    }
+
+    /// Fuehrt Handler-Arbeit mit dem Zeitlimit ihrer Zugriffsart aus
+    ///
+    /// Lesende Arbeit wird bei Zeitueberschreitung verworfen. Schreibende
+    /// Arbeit laeuft als lokaler Task immer zu Ende (kein Teilzustand), nur
+    /// die Antwort entfaellt. Erfordert eine laufende `LocalSet`.
+    ///
+    /// Ein belegter `platz` wird erst mit dem Ende der Arbeit frei.
+    async fn begrenzt<T: 'static>(
+        &self,
+        art: Zugriffsart,
+        platz: Option<AnfragePlatz>,
+        arbeit: impl Future<Output = T> + 'static,
+    ) -> Result<T, Zeitueberschreitung> {
+        let arbeit = async move {
+            let ergebnis = arbeit.await;
+            drop(platz);
+            ergebnis
+        };
+        let limits = &self.state.config.zeitlimits;
+        let metriken = &self.state.zeitlimit_metriken;
+        match art {
+            Zugriffsart::Lesen => zeitlimit::begrenzen(art, limits, metriken, arbeit).await,
+            Zugriffsart::Schreiben => {
+                let task = tokio::task::spawn_local(arbeit);
+                zeitlimit::begrenzen(art, limits, metriken, task)
+                    .await
+                    .map(|ergebnis| {
+                        ergebnis.unwrap_or_else(|e| std::panic::resume_unwind(e.into_panic()))
+                    })
+            }
         }
     }
 
     /// Bereinigt alle Ressourcen eines Clients beim Trennen
     pub async fn client_cleanup(&self, user_id: &UserId) {
+        if let Some(channel_id) = self.state.presence.channel_von_client(user_id) {
+            voice_handler::ssrc_melden(&self.state, *user_id, channel_id, None);
+        }
         self.state.presence.client_getrennt(user_id);
         self.state.broadcaster.client_entfernen(user_id);
         self.state.voice_state.client_entfernen(user_id);
         self.state.channel_router.kanal_verlassen(user_id);
+        self.state.langsammodus.verlassen(user_id);
+        self.state.aktivitaet.entfernen(user_id);
+        self.state.trennungen.ziel_getrennt(user_id);
 
         tracing::debug!(user_id = %user_id, "Client-Ressourcen bereinigt");
     }
 }
+
+/// Ordnet eine authentifizierte Nachricht einer Zugriffsart zu
+///
+/// Alles was nicht ausdruecklich nur liest, gilt als schreibend.
+fn zugriffsart(payload: &ControlPayload) -> Zugriffsart {
+    match payload {
+        ControlPayload::ChannelList(_)
+        | ControlPayload::ChannelTreeExpand(_)
+        | ControlPayload::ChannelMembers(_)
+        | ControlPayload::ClientList
+        | ControlPayload::StateDiff(_)
+        | ControlPayload::ResolveIds(_)
+        | ControlPayload::ServerInfo
+        | ControlPayload::PermissionList { .. }
+        | ControlPayload::EffectivePermissions(_)
+        | ControlPayload::ChatHistory(_)
+        | ControlPayload::ChatSearch(_)
+        | ControlPayload::FileDownload(_)
+        | ControlPayload::VoiceStats(_) => Zugriffsart::Lesen,
+        _ => Zugriffsart::Schreiben,
+    }
+}
+
+/// Nachrichten, die nicht gegen die Anfragegrenzen zaehlen
+fn ist_leichtgewichtig(payload: &ControlPayload) -> bool {
+    matches!(
+        payload,
+        ControlPayload::Hello(_)
+            | ControlPayload::Ping(_)
+            | ControlPayload::Pong(_)
+            | ControlPayload::ClientActivity
+    )
+}
+
+/// Nachrichten, die zusaetzlich einen serverweiten Datenbank-Platz brauchen
+fn ist_datenbanklastig(payload: &ControlPayload) -> bool {
+    matches!(
+        payload,
+        ControlPayload::ChatHistory(_)
+            | ControlPayload::ChatSearch(_)
+            | ControlPayload::ResolveIds(_)
+            | ControlPayload::ChannelList(_)
+            | ControlPayload::ChannelTreeExpand(_)
+            | ControlPayload::PermissionList { .. }
+            | ControlPayload::EffectivePermissions(_)
+    )
+}
+
+/// Fehlerantwort bei ueberschrittenem Zeitlimit
+fn zeitueberschreitung_antwort(request_id: u32, e: Zeitueberschreitung) -> ControlMessage {
+    ControlMessage::fehler(request_id, SpeakeasyError::Zeitlimit(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::server_state::SignalingConfig;
+    use crate::test_hilfen::TestAufbau;
+    use speakeasy_core::FehlerCode;
+    use speakeasy_db::{zeitlimit::Zeitlimits, SqliteDb};
+    use std::cell::Cell;
+    use std::rc::Rc;
+    use std::time::Duration;
+
+    const LIMITS: Zeitlimits = Zeitlimits {
+        lesen: Duration::from_millis(50),
+        schreiben: Duration::from_millis(100),
+    };
+
+    async fn dispatcher() -> MessageDispatcher<SqliteDb, SqliteDb, SqliteDb> {
+        dispatcher_mit(LIMITS).await
+    }
+
+    async fn dispatcher_mit(
+        zeitlimits: Zeitlimits,
+    ) -> MessageDispatcher<SqliteDb, SqliteDb, SqliteDb> {
+        MessageDispatcher::neu(TestAufbau::default().zeitlimits(zeitlimits).bauen().await)
+    }
+
+    async fn dispatcher_mit_config(
+        config: SignalingConfig,
+    ) -> MessageDispatcher<SqliteDb, SqliteDb, SqliteDb> {
+        MessageDispatcher::neu(TestAufbau::default().config(config).bauen().await)
+    }
+
+    #[test]
+    fn lesende_nachrichten() {
+        assert_eq!(
+            zugriffsart(&ControlPayload::ChannelList(Default::default())),
+            Zugriffsart::Lesen
+        );
+        assert_eq!(zugriffsart(&ControlPayload::ServerInfo), Zugriffsart::Lesen);
+        assert_eq!(
+            zugriffsart(&ControlPayload::ClientActivity),
+            Zugriffsart::Schreiben
+        );
+    }
+
+    #[tokio::test]
+    async fn lesen_wird_abgebrochen() {
+        let dispatcher = dispatcher().await;
+        let ergebnis = dispatcher
+            .begrenzt(Zugriffsart::Lesen, None, std::future::pending::<()>())
+            .await;
+        let e = ergebnis.unwrap_err();
+        assert_eq!(e.art, Zugriffsart::Lesen);
+
+        let antwort = zeitueberschreitung_antwort(7, e);
+        assert_eq!(antwort.request_id, 7);
+        assert!(matches!(antwort.payload, ControlPayload::Error(_)));
+        assert_eq!(
+            dispatcher
+                .state
+                .zeitlimit_metriken
+                .statistik()
+                .zeitueberschreitungen_lesen,
+            1
+        );
+    }
+
+    #[tokio::test]
+    async fn schreiben_laeuft_nach_zeitlimit_zu_ende() {
+        let dispatcher = dispatcher().await;
+        tokio::task::LocalSet::new()
+            .run_until(async {
+                let fertig = Rc::new(Cell::new(false));
+                let merker = Rc::clone(&fertig);
+                let ergebnis = dispatcher
+                    .begrenzt(Zugriffsart::Schreiben, None, async move {
+                        tokio::time::sleep(Duration::from_millis(200)).await;
+                        merker.set(true);
+                    })
+                    .await;
+                assert!(ergebnis.is_err());
+                assert!(!fertig.get());
+
+                // Die Schreiboperation wird nicht abgebrochen
+                tokio::time::sleep(Duration::from_millis(300)).await;
+                assert!(fertig.get());
+            })
+            .await;
+        assert_eq!(
+            dispatcher
+                .state
+                .zeitlimit_metriken
+                .statistik()
+                .zeitueberschreitungen_schreiben,
+            1
+        );
+    }
+
+    fn kontext() -> DispatcherContext {
+        DispatcherContext {
+            peer_addr: "127.0.0.1:50000".parse().unwrap(),
+            session_token: None,
+            user_id: None,
+            protokoll: Some(ProtokollVersion::AKTUELL),
+            shutdown_tx: tokio::sync::watch::channel(false).0,
+            zwischenmeldungen: Vec::new(),
+            anfragen: VerbindungsAnfragen::neu(),
+            drossel: VerbindungsDrossel::neu(Default::default()),
+        }
+    }
+
+    fn login(request_id: u32) -> ControlMessage {
+        login_als(request_id, "anna")
+    }
+
+    fn login_als(request_id: u32, username: &str) -> ControlMessage {
+        ControlMessage::new(
+            request_id,
+            ControlPayload::Login(speakeasy_protocol::control::LoginRequest {
+                username: username.into(),
+                password: "geheim123".into(),
+                token: None,
+                client_version: "test".into(),
+                display_name: None,
+                capture_consent: false,
+            }),
+        )
+    }
+
+    async fn login_antwort(
+        dispatcher: &MessageDispatcher<SqliteDb, SqliteDb, SqliteDb>,
+        ctx: &mut DispatcherContext,
+    ) -> speakeasy_protocol::control::LoginResponse {
+        match dispatcher.dispatch(login(1), ctx).await.unwrap().payload {
+            ControlPayload::LoginResponse(antwort) => antwort,
+            andere => panic!("Erwartet LoginResponse, erhalten: {andere:?}"),
+        }
+    }
+
+    /// Fehlerantwort auf einen abgelehnten Login als `ErrorResponse`
+    async fn login_abgelehnt(
+        dispatcher: &MessageDispatcher<SqliteDb, SqliteDb, SqliteDb>,
+        username: &str,
+    ) -> speakeasy_protocol::control::ErrorResponse {
+        let mut ctx = kontext();
+        match dispatcher
+            .dispatch(login_als(1, username), &mut ctx)
+            .await
+            .unwrap()
+            .payload
+        {
+            ControlPayload::Error(fehler) => fehler,
+            andere => panic!("Erwartet Error, erhalten: {andere:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn hello_wird_vor_dem_login_beantwortet() {
+        let dispatcher = dispatcher().await;
+        let mut ctx = kontext();
+        let hello = speakeasy_protocol::handshake::hello("test-client");
+        let antwort = dispatcher
+            .dispatch(
+                ControlMessage::new(1, ControlPayload::Hello(hello)),
+                &mut ctx,
+            )
+            .await
+            .unwrap();
+        assert_eq!(antwort.request_id, 1);
+        let ControlPayload::Welcome(welcome) = antwort.payload else {
+            panic!("Erwartet Welcome, erhalten: {:?}", antwort.payload);
+        };
+        assert_eq!(
+            welcome.protocol_version,
+            speakeasy_protocol::control::ProtokollVersion::AKTUELL
+        );
+        assert!(ctx.user_id.is_none());
+    }
+
+    #[tokio::test]
+    async fn login_erst_nach_hello() {
+        let dispatcher = dispatcher().await;
+        let mut ctx = kontext();
+        ctx.protokoll = None;
+
+        let antwort = dispatcher.dispatch(login_als(1, "anna"), &mut ctx).await;
+        match antwort.unwrap().payload {
+            ControlPayload::Error(fehler) => {
+                assert_eq!(fehler.fehler_code(), FehlerCode::UngueltigeAnfrage);
+                assert!(fehler.message.contains("Hello"));
+            }
+            andere => panic!("Erwartet Error, erhalten: {andere:?}"),
+        }
+
+        let hello = speakeasy_protocol::handshake::hello("test-client");
+        dispatcher
+            .dispatch(
+                ControlMessage::new(2, ControlPayload::Hello(hello)),
+                &mut ctx,
+            )
+            .await;
+        assert_eq!(ctx.protokoll, Some(ProtokollVersion::AKTUELL));
+    }
+
+    #[tokio::test]
+    async fn hello_mit_anderer_major_version_wird_abgelehnt() {
+        let dispatcher = dispatcher().await;
+        let mut ctx = kontext();
+        ctx.protokoll = None;
+        let mut hello = speakeasy_protocol::handshake::hello("zukunft");
+        hello.protocol_version.major += 1;
+
+        let antwort = dispatcher
+            .dispatch(
+                ControlMessage::new(1, ControlPayload::Hello(hello)),
+                &mut ctx,
+            )
+            .await;
+        match antwort.unwrap().payload {
+            ControlPayload::Error(fehler) => {
+                assert_eq!(fehler.fehler_code(), FehlerCode::ProtokollVersion);
+            }
+            andere => panic!("Erwartet Error, erhalten: {andere:?}"),
+        }
+        assert!(ctx.protokoll.is_none());
+    }
+
+    #[tokio::test]
+    async fn ohne_hello_pflicht_genuegt_login() {
+        let dispatcher = dispatcher_mit_config(SignalingConfig {
+            hello_erforderlich: false,
+            ..Default::default()
+        })
+        .await;
+        tokio::task::LocalSet::new()
+            .run_until(async {
+                let mut ctx = kontext();
+                ctx.protokoll = None;
+                let antwort = dispatcher.dispatch(login_als(1, "niemand"), &mut ctx).await;
+                // Scheitert erst an den Anmeldedaten, nicht am fehlenden Hello
+                match antwort.unwrap().payload {
+                    ControlPayload::Error(fehler) => {
+                        assert_eq!(fehler.fehler_code(), FehlerCode::UngueltigeAnmeldedaten);
+                    }
+                    andere => panic!("Erwartet Error, erhalten: {andere:?}"),
+                }
+            })
+            .await;
+    }
+
+    #[tokio::test]
+    async fn anfrageflut_wird_gedrosselt_und_eskaliert() {
+        use crate::drosselung::{DrosselLimits, EimerLimit};
+
+        let limits = DrosselLimits {
+            anmeldung: EimerLimit::neu(1, 2),
+            trennen_ab: 3,
+            ip_sperre: Some(Duration::from_secs(300)),
+            ..Default::default()
+        };
+        let dispatcher = dispatcher_mit_config(SignalingConfig {
+            drosselung: limits,
+            ..Default::default()
+        })
+        .await;
+        let mut ctx = kontext();
+        ctx.drossel = VerbindungsDrossel::neu(limits);
+        let hello = |request_id| {
+            ControlMessage::new(
+                request_id,
+                ControlPayload::Hello(speakeasy_protocol::handshake::hello("test-client")),
+            )
+        };
+
+        for request_id in 1..=2 {
+            let antwort = dispatcher.dispatch(hello(request_id), &mut ctx).await;
+            assert!(matches!(
+                antwort.unwrap().payload,
+                ControlPayload::Welcome(_)
+            ));
+        }
+        for request_id in 3..=5 {
+            assert!(!ctx.drossel.eskaliert());
+            let antwort = dispatcher.dispatch(hello(request_id), &mut ctx).await;
+            match antwort.unwrap().payload {
+                ControlPayload::Error(fehler) => {
+                    assert_eq!(fehler.code, ErrorCode::RateLimited);
+                    assert!(fehler.details.unwrap()["retry_after_secs"].as_u64() >= Some(59));
+                }
+                andere => panic!("Erwartet Error, erhalten: {andere:?}"),
+            }
+        }
+        assert!(ctx.drossel.eskaliert());
+
+        // Pings haben einen eigenen Eimer
+        let ping = ControlMessage::ping(6, 0);
+        assert!(matches!(
+            dispatcher.dispatch(ping, &mut ctx).await.unwrap().payload,
+            ControlPayload::Pong(_)
+        ));
+
+        let ban = dispatcher
+            .state
+            .ban_service
+            .aktiver_ban(None, Some("127.0.0.1"))
+            .await
+            .unwrap()
+            .expect("automatischer IP-Ban erwartet");
+        assert!(ban.banned_by.is_none());
+        assert!(ban.expires_at.is_some());
+    }
+
+    #[tokio::test]
+    async fn gebannter_benutzer_wird_abgelehnt() {
+        let dispatcher = dispatcher_mit(Zeitlimits::default()).await;
+        let state = &dispatcher.state;
+        let anna = state
+            .auth_service
+            .registrieren("anna", "geheim123")
+            .await
+            .unwrap();
+        let ban = state
+            .ban_service
+            .benutzer_bannen(None, anna.id, "Spam", Some(Duration::from_secs(3600)))
+            .await
+            .unwrap();
+
+        tokio::task::LocalSet::new()
+            .run_until(async {
+                let fehler = login_abgelehnt(&dispatcher, "anna").await;
+                assert_eq!(fehler.code, ErrorCode::Banned);
+                let details = fehler.details.expect("Details erwartet");
+                assert_eq!(details["code"], "auth.banned");
+                assert_eq!(details["reason"], "Spam");
+                assert_eq!(details["expires_at"], ban.expires_at.unwrap().timestamp());
+                assert!(state.presence.client_presence(&UserId(anna.id)).is_none());
+            })
+            .await;
+    }
+
+    #[tokio::test]
+    async fn abgelaufener_ban_erlaubt_login() {
+        let dispatcher = dispatcher_mit(Zeitlimits::default()).await;
+        let state = &dispatcher.state;
+        let anna = state
+            .auth_service
+            .registrieren("anna", "geheim123")
+            .await
+            .unwrap();
+        BanRepository::create(
+            state.db.as_ref(),
+            speakeasy_db::models::NeuerBan {
+                user_id: Some(anna.id),
+                ip: None,
+                reason: "Abgesessen",
+                banned_by: None,
+                expires_at: Some(chrono::Utc::now() - chrono::Duration::minutes(5)),
+            },
+        )
+        .await
+        .unwrap();
+
+        tokio::task::LocalSet::new()
+            .run_until(async {
+                let mut ctx = kontext();
+                let antwort = login_antwort(&dispatcher, &mut ctx).await;
+                assert_eq!(antwort.user_id, UserId(anna.id));
+            })
+            .await;
+        assert_eq!(state.ban_service.abgelaufene_bereinigen().await.unwrap(), 1);
+    }
+
+    #[tokio::test]
+    async fn ip_ban_sperrt_auch_andere_benutzernamen() {
+        let dispatcher = dispatcher_mit(Zeitlimits::default()).await;
+        let state = &dispatcher.state;
+        let anna = state
+            .auth_service
+            .registrieren("anna", "geheim123")
+            .await
+            .unwrap();
+        state
+            .auth_service
+            .registrieren("bert", "geheim123")
+            .await
+            .unwrap();
+        state
+            .ban_service
+            .benutzer_bannen(None, anna.id, "Spam", None)
+            .await
+            .unwrap();
+        state
+            .ban_service
+            .ip_bannen(None, "127.0.0.1", "Spam", None)
+            .await
+            .unwrap();
+
+        tokio::task::LocalSet::new()
+            .run_until(async {
+                let fehler = login_abgelehnt(&dispatcher, "bert").await;
+                assert_eq!(fehler.code, ErrorCode::Banned);
+                let details = fehler.details.expect("Details erwartet");
+                assert_eq!(details["reason"], "Spam");
+                assert!(details["expires_at"].is_null());
+            })
+            .await;
+    }
+
+    #[tokio::test]
+    async fn geaenderte_willkommensnachricht_und_motd_gelten_beim_naechsten_login() {
+        // Passwort-Hashing braucht im Debug-Build laenger als die Test-Limits
+        let dispatcher = dispatcher_mit(Zeitlimits::default()).await;
+        dispatcher
+            .state
+            .auth_service
+            .registrieren("anna", "geheim123")
+            .await
+            .unwrap();
+
+        tokio::task::LocalSet::new()
+            .run_until(async {
+                let mut ctx = kontext();
+                let antwort = login_antwort(&dispatcher, &mut ctx).await;
+                assert_eq!(antwort.welcome_message, None);
+                assert!(antwort.motd.is_none());
+
+                let mut einstellungen = dispatcher.state.einstellungen.aktuell();
+                einstellungen.willkommensnachricht = Some("Neu hier?".into());
+                dispatcher.state.einstellungen_uebernehmen(einstellungen);
+                let motd = |markdown: &str, version| speakeasy_db::motd::Motd {
+                    markdown: markdown.into(),
+                    version,
+                };
+                dispatcher
+                    .state
+                    .einstellungen
+                    .motd_uebernehmen(motd("**Neu**", 2));
+                // Ueberholtes Ereignis: aeltere Version bleibt wirkungslos
+                dispatcher
+                    .state
+                    .einstellungen
+                    .motd_uebernehmen(motd("alt", 1));
+
+                // Neu verbinden: Abmelden und mit frischem Kontext anmelden
+                let abmelden = ControlMessage::new(
+                    2,
+                    ControlPayload::Logout(speakeasy_protocol::control::LogoutRequest {
+                        reason: None,
+                    }),
+                );
+                dispatcher.dispatch(abmelden, &mut ctx).await;
+
+                let mut ctx = kontext();
+                let antwort = login_antwort(&dispatcher, &mut ctx).await;
+                assert_eq!(antwort.welcome_message.as_deref(), Some("Neu hier?"));
+                let motd = antwort.motd.expect("MOTD erwartet");
+                assert_eq!((motd.markdown.as_str(), motd.version), ("**Neu**", 2));
+            })
+            .await;
+    }
+
+    #[tokio::test]
+    async fn konto_loeschen_meldet_verbindung_ab() {
+        use speakeasy_chat::{DiskStorage, KontoKonfig, KontoService};
+
+        let dispatcher = dispatcher_mit(Zeitlimits::default()).await;
+        let state = &dispatcher.state;
+        let anna = state
+            .auth_service
+            .registrieren("anna", "geheim123")
+            .await
+            .unwrap();
+        let speicher = std::env::temp_dir().join(format!("speakeasy-konto-{}", anna.id));
+        state.konto_dienst_setzen(KontoService::neu(
+            Arc::clone(&state.db),
+            Arc::new(DiskStorage::new(&speicher)),
+            KontoKonfig::default(),
+        ));
+        let loeschen = |request_id, passwort: &str| {
+            ControlMessage::new(
+                request_id,
+                ControlPayload::AccountDelete(speakeasy_protocol::control::AccountDeleteRequest {
+                    password_confirmation: passwort.into(),
+                }),
+            )
+        };
+
+        tokio::task::LocalSet::new()
+            .run_until(async {
+                let mut ctx = kontext();
+                let login = login_antwort(&dispatcher, &mut ctx).await;
+
+                let antwort = dispatcher.dispatch(loeschen(2, "falsch"), &mut ctx).await;
+                match antwort.unwrap().payload {
+                    ControlPayload::Error(fehler) => {
+                        assert_eq!(fehler.code, ErrorCode::InvalidCredentials)
+                    }
+                    andere => panic!("Erwartet Error, erhalten: {andere:?}"),
+                }
+                assert!(ctx.user_id.is_some());
+
+                let antwort = dispatcher
+                    .dispatch(loeschen(3, "geheim123"), &mut ctx)
+                    .await;
+                assert!(matches!(
+                    antwort.unwrap().payload,
+                    ControlPayload::AccountDeleteResponse(_)
+                ));
+                assert!(ctx.user_id.is_none() && ctx.session_token.is_none());
+                assert!(state
+                    .auth_service
+                    .session_validieren(&login.session_token)
+                    .await
+                    .is_err());
+                assert!(UserRepository::get_by_id(state.db.as_ref(), anna.id)
+                    .await
+                    .unwrap()
+                    .is_none());
+            })
+            .await;
+        let _ = std::fs::remove_dir_all(speicher);
+    }
+
+    #[tokio::test]
+    async fn session_verlaengern_bis_zum_cleanup() {
+        let dispatcher = dispatcher_mit(Zeitlimits::default()).await;
+        let state = &dispatcher.state;
+        state
+            .auth_service
+            .registrieren("anna", "geheim123")
+            .await
+            .unwrap();
+        let verlaengern =
+            |request_id| ControlMessage::new(request_id, ControlPayload::SessionRenew);
+
+        tokio::task::LocalSet::new()
+            .run_until(async {
+                let mut ctx = kontext();
+                let login = login_antwort(&dispatcher, &mut ctx).await;
+
+                let antwort = dispatcher.dispatch(verlaengern(2), &mut ctx).await;
+                match antwort.unwrap().payload {
+                    ControlPayload::SessionRenewResponse(r) => {
+                        assert!(r.expires_at >= login.expires_at)
+                    }
+                    andere => panic!("Erwartet SessionRenewResponse, erhalten: {andere:?}"),
+                }
+
+                // Session zwischen Warnung und Verlaengerung entfernt
+                state
+                    .auth_service
+                    .abmelden(&login.session_token)
+                    .await
+                    .unwrap();
+                let antwort = dispatcher.dispatch(verlaengern(3), &mut ctx).await;
+                match antwort.unwrap().payload {
+                    ControlPayload::Error(fehler) => {
+                        assert_eq!(fehler.code, ErrorCode::SessionExpired)
+                    }
+                    andere => panic!("Erwartet Error, erhalten: {andere:?}"),
+                }
+            })
+            .await;
+    }
+
+    #[tokio::test]
+    async fn download_link_fuer_hochgeladene_datei() {
+        use speakeasy_chat::{
+            DateiUpload, DiskStorage, DownloadKonfig, DownloadService, FileService,
+        };
+        use speakeasy_db::models::{KanalTyp, NeuerKanal};
+
+        let dispatcher = dispatcher_mit(Zeitlimits::default()).await;
+        let state = &dispatcher.state;
+        let anna = state
+            .auth_service
+            .registrieren("anna", "geheim123")
+            .await
+            .unwrap();
+        let kanal = ChannelRepository::create(
+            state.db.as_ref(),
+            NeuerKanal {
+                name: "Dateien",
+                channel_type: KanalTyp::Text,
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        let speicher = std::env::temp_dir().join(format!("speakeasy-download-{}", anna.id));
+        let files = FileService::neu(
+            Arc::clone(&state.db),
+            Arc::clone(&state.db),
+            Arc::new(DiskStorage::new(&speicher)),
+        );
+        let (datei, _) = files
+            .datei_hochladen(
+                DateiUpload {
+                    channel_id: kanal.id,
+                    uploader_id: anna.id,
+                    filename: "plan.txt".into(),
+                    mime_type: "text/plain".into(),
+                    data: b"Plan B".to_vec(),
+                },
+                None,
+            )
+            .await
+            .unwrap();
+        let anfrage = |request_id, file_id: String| {
+            ControlMessage::new(
+                request_id,
+                ControlPayload::FileDownload(speakeasy_protocol::control::FileDownloadRequest {
+                    file_id,
+                }),
+            )
+        };
+
+        tokio::task::LocalSet::new()
+            .run_until(async {
+                let mut ctx = kontext();
+                login_antwort(&dispatcher, &mut ctx).await;
+
+                // Ohne Dienst abgelehnt
+                let antwort = dispatcher
+                    .dispatch(anfrage(2, datei.id.to_string()), &mut ctx)
+                    .await;
+                assert!(matches!(antwort.unwrap().payload, ControlPayload::Error(_)));
+
+                state.datei_dienst_setzen(DownloadService::mit_zufallsschluessel(
+                    files,
+                    DownloadKonfig::default(),
+                ));
+                let antwort = dispatcher
+                    .dispatch(anfrage(3, datei.id.to_string()), &mut ctx)
+                    .await;
+                match antwort.unwrap().payload {
+                    ControlPayload::FileDownloadResponse(link) => {
+                        assert_eq!(link.filename, "plan.txt");
+                        assert_eq!(link.size_bytes, 6);
+                        assert_eq!(link.sha256.len(), 64);
+                        assert!(link
+                            .download_url
+                            .starts_with("http://localhost:9301/files/download/"));
+                        assert!(link.expires_in_secs > 0);
+                    }
+                    andere => panic!("Erwartet FileDownloadResponse, erhalten: {andere:?}"),
+                }
+
+                let antwort = dispatcher
+                    .dispatch(anfrage(4, "keine-uuid".into()), &mut ctx)
+                    .await;
+                match antwort.unwrap().payload {
+                    ControlPayload::Error(fehler) => {
+                        assert_eq!(fehler.code, ErrorCode::InvalidRequest)
+                    }
+                    andere => panic!("Erwartet Error, erhalten: {andere:?}"),
+                }
+            })
+            .await;
+        let _ = std::fs::remove_dir_all(speicher);
+    }
+
+    #[tokio::test]
+    async fn upload_link_fuer_angemeldete_datei() {
+        use speakeasy_chat::{DiskStorage, FileService, UploadKonfig, UploadService};
+        use speakeasy_db::models::{KanalTyp, NeuerKanal};
+        use speakeasy_protocol::control::FileUploadRequest;
+
+        let dispatcher = dispatcher_mit(Zeitlimits::default()).await;
+        let state = &dispatcher.state;
+        state
+            .auth_service
+            .registrieren("anna", "geheim123")
+            .await
+            .unwrap();
+        let kanal = ChannelRepository::create(
+            state.db.as_ref(),
+            NeuerKanal {
+                name: "Dateien",
+                channel_type: KanalTyp::Text,
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        let speicher = std::env::temp_dir().join(format!("speakeasy-upload-{}", kanal.id));
+        let files = FileService::neu(
+            Arc::clone(&state.db),
+            Arc::clone(&state.db),
+            Arc::new(DiskStorage::new(&speicher)),
+        );
+        let anfrage = |request_id, checksum: Option<String>| {
+            ControlMessage::new(
+                request_id,
+                ControlPayload::FileUpload(FileUploadRequest {
+                    channel_id: speakeasy_core::types::ChannelId(kanal.id),
+                    filename: "plan.txt".into(),
+                    size_bytes: 6,
+                    mime_type: None,
+                    checksum,
+                }),
+            )
+        };
+
+        tokio::task::LocalSet::new()
+            .run_until(async {
+                let mut ctx = kontext();
+                login_antwort(&dispatcher, &mut ctx).await;
+
+                // Ohne Dienst abgelehnt
+                let antwort = dispatcher.dispatch(anfrage(2, None), &mut ctx).await;
+                assert!(matches!(antwort.unwrap().payload, ControlPayload::Error(_)));
+
+                let dienst = UploadService::mit_zufallsschluessel(files, UploadKonfig::default());
+                state.upload_dienst_setzen(dienst.clone());
+                // SHA-256 von "Plan B"
+                let pruefsumme =
+                    "915c4962dcba1db26a2fbc31678a5aff64fa6b2865424da24d494a2562ce6108".to_string();
+                let antwort = dispatcher
+                    .dispatch(anfrage(3, Some(pruefsumme)), &mut ctx)
+                    .await;
+                let link = match antwort.unwrap().payload {
+                    ControlPayload::FileUploadResponse(link) => link,
+                    andere => panic!("Erwartet FileUploadResponse, erhalten: {andere:?}"),
+                };
+                assert!(link
+                    .upload_url
+                    .starts_with("http://localhost:9301/files/upload/"));
+                assert!(link.expires_in_secs > 0);
+
+                // Der Link gilt fuer genau diese Datei
+                let token = link.upload_url.rsplit('/').next().unwrap();
+                let (info, nachricht) = dienst.hochladen(token, b"Plan B").await.unwrap();
+                assert_eq!(info.id.to_string(), link.file_id);
+                assert_eq!(nachricht.channel_id, kanal.id);
+                assert_eq!(info.mime_type, "application/octet-stream");
+
+                let antwort = dispatcher
+                    .dispatch(anfrage(4, Some("kaputt".into())), &mut ctx)
+                    .await;
+                assert!(matches!(antwort.unwrap().payload, ControlPayload::Error(_)));
+            })
+            .await;
+        let _ = std::fs::remove_dir_all(speicher);
+    }
+
+    #[tokio::test]
+    async fn datei_links_nur_mit_kanalzugriff() {
+        use speakeasy_chat::{
+            DateiUpload, DiskStorage, DownloadKonfig, DownloadService, FileService, UploadKonfig,
+            UploadService,
+        };
+        use speakeasy_db::models::{
+            BerechtigungsWert, BerechtigungsZiel, KanalTyp, NeuerKanal, TriState,
+        };
+        use speakeasy_db::PermissionRepository;
+        use speakeasy_protocol::control::{FileDownloadRequest, FileUploadRequest};
+
+        let dispatcher = dispatcher_mit(Zeitlimits::default()).await;
+        let state = &dispatcher.state;
+        let anna = state
+            .auth_service
+            .registrieren("anna", "geheim123")
+            .await
+            .unwrap();
+        let kanal = ChannelRepository::create(
+            state.db.as_ref(),
+            NeuerKanal {
+                name: "Vorstand",
+                channel_type: KanalTyp::Text,
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        state
+            .db
+            .set_permission(
+                &BerechtigungsZiel::Benutzer(anna.id),
+                "b_channel_join",
+                BerechtigungsWert::TriState(TriState::Deny),
+                Some(kanal.id),
+            )
+            .await
+            .unwrap();
+        let speicher = std::env::temp_dir().join(format!("speakeasy-zugriff-{}", kanal.id));
+        let files = FileService::neu(
+            Arc::clone(&state.db),
+            Arc::clone(&state.db),
+            Arc::new(DiskStorage::new(&speicher)),
+        );
+        // Von jemand anderem dort gepostet
+        let (datei, _) = files
+            .datei_hochladen(
+                DateiUpload {
+                    channel_id: kanal.id,
+                    uploader_id: anna.id,
+                    filename: "bilanz.txt".into(),
+                    mime_type: "text/plain".into(),
+                    data: b"geheim".to_vec(),
+                },
+                None,
+            )
+            .await
+            .unwrap();
+        state.datei_dienst_setzen(DownloadService::mit_zufallsschluessel(
+            Arc::clone(&files),
+            DownloadKonfig::default(),
+        ));
+        state.upload_dienst_setzen(UploadService::mit_zufallsschluessel(
+            files,
+            UploadKonfig::default(),
+        ));
+        let hochladen = |request_id, channel_id| {
+            ControlMessage::new(
+                request_id,
+                ControlPayload::FileUpload(FileUploadRequest {
+                    channel_id: speakeasy_core::types::ChannelId(channel_id),
+                    filename: "plan.txt".into(),
+                    size_bytes: 6,
+                    mime_type: None,
+                    checksum: None,
+                }),
+            )
+        };
+        let fehlercode = |antwort: Option<ControlMessage>| match antwort.unwrap().payload {
+            ControlPayload::Error(fehler) => fehler.code,
+            andere => panic!("Erwartet Error, erhalten: {andere:?}"),
+        };
+
+        tokio::task::LocalSet::new()
+            .run_until(async {
+                let mut ctx = kontext();
+                login_antwort(&dispatcher, &mut ctx).await;
+
+                let antwort = dispatcher.dispatch(hochladen(2, kanal.id), &mut ctx).await;
+                assert_eq!(fehlercode(antwort), ErrorCode::PermissionDenied);
+
+                let antwort = dispatcher
+                    .dispatch(hochladen(3, uuid::Uuid::new_v4()), &mut ctx)
+                    .await;
+                assert_eq!(fehlercode(antwort), ErrorCode::NotFound);
+
+                let download = ControlMessage::new(
+                    4,
+                    ControlPayload::FileDownload(FileDownloadRequest {
+                        file_id: datei.id.to_string(),
+                    }),
+                );
+                let antwort = dispatcher.dispatch(download, &mut ctx).await;
+                assert_eq!(fehlercode(antwort), ErrorCode::PermissionDenied);
+            })
+            .await;
+        let _ = std::fs::remove_dir_all(speicher);
+    }
+
+    /// Kanal mit `anzahl` Nachrichten, Kontext als deren Absender angemeldet
+    async fn kanal_mit_verlauf(
+        dispatcher: &MessageDispatcher<SqliteDb, SqliteDb, SqliteDb>,
+        anzahl: usize,
+    ) -> (DispatcherContext, speakeasy_core::types::ChannelId) {
+        use speakeasy_db::models::{NeuerBenutzer, NeuerKanal};
+
+        let db = dispatcher.state.db.as_ref();
+        let sender = UserRepository::create(
+            db,
+            NeuerBenutzer {
+                username: "anna",
+                password_hash: "hash",
+            },
+        )
+        .await
+        .unwrap()
+        .id;
+        let kanal = ChannelRepository::create(
+            db,
+            NeuerKanal {
+                name: "Lobby",
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap()
+        .id;
+        for nr in 0..anzahl {
+            dispatcher
+                .state
+                .chat_service
+                .nachricht_senden(kanal, sender, &format!("Nachricht {nr}"), None)
+                .await
+                .unwrap();
+        }
+
+        let mut ctx = kontext();
+        ctx.user_id = Some(UserId(sender));
+        (ctx, speakeasy_core::types::ChannelId(kanal))
+    }
+
+    fn verlauf(
+        channel_id: speakeasy_core::types::ChannelId,
+        limit: i64,
+        chunked: bool,
+    ) -> ControlPayload {
+        ControlPayload::ChatHistory(speakeasy_protocol::control::ChatHistoryRequest {
+            channel_id,
+            before: None,
+            limit: Some(limit),
+            chunked,
+        })
+    }
+
+    #[tokio::test]
+    async fn chat_verlauf_in_teilstuecken() {
+        let dispatcher = dispatcher_mit(Zeitlimits::default()).await;
+        let (mut ctx, kanal) = kanal_mit_verlauf(&dispatcher, 25).await;
+
+        let gesamt = match dispatcher
+            .dispatch(ControlMessage::new(4, verlauf(kanal, 50, false)), &mut ctx)
+            .await
+            .unwrap()
+            .payload
+        {
+            ControlPayload::ChatHistoryResponse(antwort) => antwort.messages,
+            andere => panic!("Erwartet ChatHistoryResponse, erhalten: {andere:?}"),
+        };
+        assert!(ctx.zwischenmeldungen.is_empty());
+
+        let abschluss = dispatcher
+            .dispatch(ControlMessage::new(5, verlauf(kanal, 50, true)), &mut ctx)
+            .await
+            .unwrap();
+        assert_eq!(abschluss.request_id, 5);
+        match abschluss.payload {
+            ControlPayload::ChatHistoryComplete(abschluss) => {
+                assert_eq!(abschluss.chunks, 3);
+                assert_eq!(abschluss.total, 25);
+                assert_eq!(abschluss.next_before, None);
+            }
+            andere => panic!("Erwartet ChatHistoryComplete, erhalten: {andere:?}"),
+        }
+
+        let teilstuecke = std::mem::take(&mut ctx.zwischenmeldungen);
+        let mut ids = Vec::new();
+        for (index, meldung) in teilstuecke.into_iter().enumerate() {
+            assert_eq!(meldung.request_id, 5);
+            match meldung.payload {
+                ControlPayload::ChatHistoryChunk(teil) => {
+                    assert_eq!(teil.index, index as u32);
+                    ids.extend(teil.messages.into_iter().map(|n| n.message_id));
+                }
+                andere => panic!("Erwartet ChatHistoryChunk, erhalten: {andere:?}"),
+            }
+        }
+        let erwartet: Vec<_> = gesamt.into_iter().map(|n| n.message_id).collect();
+        assert_eq!(ids, erwartet);
+
+        // Volle Seite: Cursor zeigt auf die aelteste gelieferte Nachricht
+        let abschluss = dispatcher
+            .dispatch(ControlMessage::new(6, verlauf(kanal, 20, true)), &mut ctx)
+            .await
+            .unwrap();
+        let erste = match &ctx.zwischenmeldungen[0].payload {
+            ControlPayload::ChatHistoryChunk(teil) => teil.messages[0].created_at.clone(),
+            andere => panic!("Erwartet ChatHistoryChunk, erhalten: {andere:?}"),
+        };
+        match abschluss.payload {
+            ControlPayload::ChatHistoryComplete(abschluss) => {
+                assert_eq!(abschluss.total, 20);
+                assert_eq!(abschluss.next_before, Some(erste));
+            }
+            andere => panic!("Erwartet ChatHistoryComplete, erhalten: {andere:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn chat_verlauf_fehler_beendet_die_folge() {
+        let dispatcher = dispatcher_mit(Zeitlimits::default()).await;
+        let (mut ctx, kanal) = kanal_mit_verlauf(&dispatcher, 3).await;
+        dispatcher.state.db.pool().close().await;
+
+        let antwort = dispatcher
+            .dispatch(ControlMessage::new(9, verlauf(kanal, 50, true)), &mut ctx)
+            .await
+            .unwrap();
+        assert_eq!(antwort.request_id, 9);
+        assert!(matches!(antwort.payload, ControlPayload::Error(_)));
+        assert!(ctx.zwischenmeldungen.is_empty());
+    }
+
+    #[tokio::test]
+    async fn chat_suche_nur_fuer_kanalmitglieder() {
+        use crate::test_hilfen::client_anmelden;
+        use speakeasy_protocol::control::ChatSearchRequest;
+
+        let dispatcher = dispatcher_mit(Zeitlimits::default()).await;
+        let (mut ctx, kanal) = kanal_mit_verlauf(&dispatcher, 0).await;
+        let anna = ctx.user_id.unwrap();
+        let state = &dispatcher.state;
+        for inhalt in ["50% Rabatt", "500 Euro", "Treffen um 5", "it's 50% off"] {
+            state
+                .chat_service
+                .nachricht_senden(kanal.inner(), anna.inner(), inhalt, None)
+                .await
+                .unwrap();
+        }
+        let suche = |query: &str| {
+            ControlPayload::ChatSearch(ChatSearchRequest {
+                channel_id: kanal,
+                query: query.into(),
+                limit: None,
+                before: None,
+                before_id: None,
+            })
+        };
+
+        // Noch in keinem Kanal: keine Suche
+        let antwort = dispatcher
+            .dispatch(ControlMessage::new(3, suche("50%")), &mut ctx)
+            .await
+            .unwrap();
+        assert!(matches!(antwort.payload, ControlPayload::Error(_)));
+
+        client_anmelden(state, anna, Some(kanal));
+        let antwort = dispatcher
+            .dispatch(ControlMessage::new(4, suche("50%")), &mut ctx)
+            .await
+            .unwrap();
+        match antwort.payload {
+            ControlPayload::ChatSearchResponse(antwort) => {
+                let inhalte: Vec<_> = antwort
+                    .messages
+                    .iter()
+                    .map(|n| n.content.as_str())
+                    .collect();
+                assert_eq!(inhalte, ["it's 50% off", "50% Rabatt"]);
+                assert_eq!(antwort.next_before, None);
+            }
+            andere => panic!("Erwartet ChatSearchResponse, erhalten: {andere:?}"),
+        }
+
+        // Seitenweise mit Cursor: auch Treffer aus derselben Sekunde folgen
+        let mut inhalte = Vec::new();
+        let (mut before, mut before_id) = (None, None);
+        for request_id in 5.. {
+            let seite = ControlPayload::ChatSearch(ChatSearchRequest {
+                channel_id: kanal,
+                query: "50%".into(),
+                limit: Some(1),
+                before: before.take(),
+                before_id: before_id.take(),
+            });
+            let antwort = dispatcher
+                .dispatch(ControlMessage::new(request_id, seite), &mut ctx)
+                .await
+                .unwrap();
+            let ControlPayload::ChatSearchResponse(antwort) = antwort.payload else {
+                panic!("Erwartet ChatSearchResponse");
+            };
+            inhalte.extend(antwort.messages.into_iter().map(|n| n.content));
+            if antwort.next_before.is_none() {
+                break;
+            }
+            (before, before_id) = (antwort.next_before, antwort.next_before_id);
+        }
+        assert_eq!(inhalte, ["it's 50% off", "50% Rabatt"]);
+    }
+
+    #[tokio::test]
+    async fn chat_ereignisse_gehen_an_den_kanal_ausser_dem_absender() {
+        use crate::test_hilfen::client_anmelden;
+        use speakeasy_protocol::control::{ChatDeleteRequest, ChatEditRequest, ChatSendRequest};
+
+        let dispatcher = dispatcher_mit(Zeitlimits::default()).await;
+        let (mut ctx, kanal) = kanal_mit_verlauf(&dispatcher, 0).await;
+        let anna = ctx.user_id.unwrap();
+        let bert = UserId(uuid::Uuid::new_v4());
+        let state = &dispatcher.state;
+        client_anmelden(state, anna, Some(kanal));
+        state.presence.nickname_aktualisieren(anna, "Anna".into());
+        let mut anna_rx = state.broadcaster.client_registrieren(anna);
+        let mut bert_rx = state.broadcaster.client_registrieren(bert);
+        state.broadcaster.channel_beitreten(anna, kanal);
+        state.broadcaster.channel_beitreten(bert, kanal);
+
+        // Schreibende Anfragen laufen als lokaler Task weiter
+        tokio::task::LocalSet::new()
+            .run_until(async {
+                let senden = ControlPayload::ChatSend(ChatSendRequest {
+                    channel_id: kanal,
+                    content: "Hallo Bert".into(),
+                    reply_to: None,
+                });
+                let message_id = match dispatcher
+                    .dispatch(ControlMessage::new(2, senden), &mut ctx)
+                    .await
+                    .unwrap()
+                    .payload
+                {
+                    ControlPayload::ChatSendResponse(antwort) => antwort.message_id,
+                    andere => panic!("Erwartet ChatSendResponse, erhalten: {andere:?}"),
+                };
+                match bert_rx.try_recv().unwrap().payload {
+                    ControlPayload::ChatMessage(ereignis) => {
+                        assert_eq!(ereignis.message.message_id, message_id);
+                        assert_eq!(ereignis.message.sender_id, anna);
+                        assert_eq!(ereignis.message.content, "Hallo Bert");
+                        assert_eq!(ereignis.sender_name, "Anna");
+                    }
+                    andere => panic!("Erwartet ChatMessage, erhalten: {andere:?}"),
+                }
+
+                let bearbeiten = ControlPayload::ChatEdit(ChatEditRequest {
+                    message_id: message_id.clone(),
+                    content: "Hallo zusammen".into(),
+                });
+                dispatcher
+                    .dispatch(ControlMessage::new(3, bearbeiten), &mut ctx)
+                    .await
+                    .unwrap();
+                match bert_rx.try_recv().unwrap().payload {
+                    ControlPayload::ChatEdited(ereignis) => {
+                        assert_eq!(ereignis.message_id, message_id);
+                        assert_eq!(ereignis.channel_id, kanal);
+                        assert_eq!(ereignis.content, "Hallo zusammen");
+                    }
+                    andere => panic!("Erwartet ChatEdited, erhalten: {andere:?}"),
+                }
+
+                let loeschen = ControlPayload::ChatDelete(ChatDeleteRequest {
+                    message_id: message_id.clone(),
+                });
+                dispatcher
+                    .dispatch(ControlMessage::new(4, loeschen), &mut ctx)
+                    .await
+                    .unwrap();
+                match bert_rx.try_recv().unwrap().payload {
+                    ControlPayload::ChatDeleted(ereignis) => {
+                        assert_eq!(ereignis.message_id, message_id);
+                        assert_eq!(ereignis.channel_id, kanal);
+                    }
+                    andere => panic!("Erwartet ChatDeleted, erhalten: {andere:?}"),
+                }
+
+                // Der Absender kennt alles bereits aus seinen Antworten
+                assert!(anna_rx.try_recv().is_err());
+            })
+            .await;
+    }
+
+    #[tokio::test]
+    async fn verlaufsflut_wird_begrenzt_andere_verbindung_bleibt_bedient() {
+        let dispatcher = dispatcher_mit_config(SignalingConfig {
+            zeitlimits: Zeitlimits::default(),
+            anfrage_limits: crate::anfragelimit::AnfrageLimits {
+                pro_verbindung: 4,
+                ..Default::default()
+            },
+            ..Default::default()
+        })
+        .await;
+        let (ctx, kanal) = kanal_mit_verlauf(&dispatcher, 30).await;
+
+        // Viele gleichzeitige Anfragen ueber dieselbe Verbindung
+        let mut kontexte: Vec<DispatcherContext> = (0..20)
+            .map(|_| {
+                let mut gleiche = kontext();
+                gleiche.user_id = ctx.user_id;
+                gleiche.anfragen = ctx.anfragen.clone();
+                gleiche
+            })
+            .collect();
+        let flut =
+            futures_util::future::join_all(kontexte.iter_mut().enumerate().map(|(nr, gleiche)| {
+                let anfrage = ControlMessage::new(nr as u32, verlauf(kanal, 50, false));
+                dispatcher.dispatch(anfrage, gleiche)
+            }));
+
+        let mut andere = kontext();
+        andere.user_id = Some(UserId::new());
+        let andere_anfrage = dispatcher.dispatch(
+            ControlMessage::new(100, verlauf(kanal, 50, false)),
+            &mut andere,
+        );
+
+        let (antworten, andere_antwort) = tokio::time::timeout(Duration::from_secs(5), async {
+            tokio::join!(flut, andere_anfrage)
+        })
+        .await
+        .expect("Anfragen muessen in begrenzter Zeit fertig werden");
+
+        let (mut beantwortet, mut abgelehnt) = (0, 0);
+        for antwort in antworten {
+            match antwort.unwrap().payload {
+                ControlPayload::ChatHistoryResponse(_) => beantwortet += 1,
+                ControlPayload::Error(fehler) => {
+                    assert_eq!(fehler.code, ErrorCode::RateLimited);
+                    assert!(fehler.details.unwrap()["retry_after_secs"].as_u64() > Some(0));
+                    abgelehnt += 1;
+                }
+                andere => panic!("Unerwartete Antwort: {andere:?}"),
+            }
+        }
+        assert_eq!((beantwortet, abgelehnt), (4, 16));
+        assert!(matches!(
+            andere_antwort.unwrap().payload,
+            ControlPayload::ChatHistoryResponse(_)
+        ));
+
+        let statistik = dispatcher.state.anfragen.statistik();
+        assert_eq!(statistik.laufend, 0);
+        assert_eq!(statistik.abgelehnt, 16);
+        assert_eq!(ctx.anfragen.laufend(), 0);
+    }
+}
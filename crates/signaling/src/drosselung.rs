@@ -0,0 +1,406 @@
+//! Drosselung des Control-Kanals je Verbindung (Token-Bucket)
+//!
+//! Waehrend der [`AnfrageBegrenzer`](crate::anfragelimit::AnfrageBegrenzer)
+//! gleichzeitig laufende Anfragen zaehlt, begrenzt die
+//! [`VerbindungsDrossel`] die Rate: jede Nachrichtenkategorie hat einen
+//! eigenen Eimer, der mit `pro_minute` Token nachlaeuft und hoechstens
+//! `stoss` Token fasst. Jede Nachricht verbraucht ein Token; ein leerer Eimer
+//! fuehrt zu `RateLimited` mit der Wartezeit bis zum naechsten Token.
+//!
+//! Haeufen sich Ablehnungen (`trennen_ab` innerhalb von `fenster`), eskaliert
+//! die Drossel: die Verbindung wird getrennt und die IP auf Wunsch fuer
+//! `ip_sperre` automatisch gebannt.
+
+use speakeasy_protocol::control::ControlPayload;
+use std::collections::VecDeque;
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// Nachrichtenkategorie mit eigenem Eimer
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kategorie {
+    /// Handshake und Anmeldeversuche
+    Anmeldung,
+    /// Chat senden, bearbeiten, loeschen und Verlauf
+    Chat,
+    /// Kanaele anlegen, aendern, betreten und auflisten
+    Kanal,
+    /// Keepalive des Clients
+    Ping,
+    /// Alle uebrigen Anfragen
+    Sonstige,
+}
+
+impl Kategorie {
+    /// Kategorie einer eingehenden Nachricht
+    ///
+    /// `None` fuer Pong: er beantwortet nur Pings des Servers und wird nicht
+    /// gedrosselt.
+    pub fn von(payload: &ControlPayload) -> Option<Self> {
+        Some(match payload {
+            ControlPayload::Pong(_) => return None,
+            ControlPayload::Hello(_)
+            | ControlPayload::Login(_)
+            | ControlPayload::Register(_)
+            | ControlPayload::SessionRenew
+            | ControlPayload::PasswordChange(_) => Self::Anmeldung,
+            ControlPayload::ChatSend(_)
+            | ControlPayload::ChatEdit(_)
+            | ControlPayload::ChatDelete(_)
+            | ControlPayload::ChatHistory(_)
+            | ControlPayload::ChatSearch(_) => Self::Chat,
+            ControlPayload::ChannelList(_)
+            | ControlPayload::ChannelTreeExpand(_)
+            | ControlPayload::ChannelJoin(_)
+            | ControlPayload::ChannelMembers(_)
+            | ControlPayload::ChannelLeave(_)
+            | ControlPayload::ChannelCreate(_)
+            | ControlPayload::ChannelEdit(_)
+            | ControlPayload::ChannelDelete(_)
+            | ControlPayload::ChannelInvite(_)
+            | ControlPayload::ChannelInviteAnswer(_)
+            | ControlPayload::ChannelKnock(_)
+            | ControlPayload::ChannelKnockAnswer(_) => Self::Kanal,
+            ControlPayload::Ping(_) => Self::Ping,
+            _ => Self::Sonstige,
+        })
+    }
+
+    fn index(self) -> usize {
+        match self {
+            Self::Anmeldung => 0,
+            Self::Chat => 1,
+            Self::Kanal => 2,
+            Self::Ping => 3,
+            Self::Sonstige => 4,
+        }
+    }
+}
+
+/// Groesse und Nachlauf eines Eimers
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EimerLimit {
+    /// Nachlaufende Token pro Minute (0 = unbegrenzt)
+    pub pro_minute: u32,
+    /// Fassungsvermoegen, also die erlaubte Spitze am Stueck
+    pub stoss: u32,
+}
+
+impl EimerLimit {
+    pub const fn neu(pro_minute: u32, stoss: u32) -> Self {
+        Self { pro_minute, stoss }
+    }
+
+    fn unbegrenzt(self) -> bool {
+        self.pro_minute == 0
+    }
+
+    fn pro_sekunde(self) -> f64 {
+        f64::from(self.pro_minute) / 60.0
+    }
+}
+
+/// Grenzen der Drosselung je Verbindung
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DrosselLimits {
+    pub anmeldung: EimerLimit,
+    pub chat: EimerLimit,
+    pub kanal: EimerLimit,
+    pub ping: EimerLimit,
+    pub sonstige: EimerLimit,
+    /// So viele Ablehnungen innerhalb von `fenster` trennen die Verbindung
+    /// (0 = nie trennen)
+    pub trennen_ab: usize,
+    /// Zeitfenster fuer die Eskalation
+    pub fenster: Duration,
+    /// Dauer des automatischen IP-Banns beim Trennen (`None` = kein Ban)
+    pub ip_sperre: Option<Duration>,
+}
+
+impl Default for DrosselLimits {
+    fn default() -> Self {
+        Self {
+            anmeldung: EimerLimit::neu(12, 5),
+            chat: EimerLimit::neu(120, 10),
+            kanal: EimerLimit::neu(60, 10),
+            ping: EimerLimit::neu(60, 5),
+            sonstige: EimerLimit::neu(600, 40),
+            trennen_ab: 10,
+            fenster: Duration::from_secs(60),
+            ip_sperre: None,
+        }
+    }
+}
+
+impl DrosselLimits {
+    fn eimer(&self, kategorie: Kategorie) -> EimerLimit {
+        match kategorie {
+            Kategorie::Anmeldung => self.anmeldung,
+            Kategorie::Chat => self.chat,
+            Kategorie::Kanal => self.kanal,
+            Kategorie::Ping => self.ping,
+            Kategorie::Sonstige => self.sonstige,
+        }
+    }
+}
+
+/// Token-Bucket einer Kategorie
+#[derive(Debug, Clone)]
+struct Eimer {
+    token: f64,
+    letzte_auffuellung: Instant,
+}
+
+impl Eimer {
+    fn voll(limit: EimerLimit, jetzt: Instant) -> Self {
+        Self {
+            token: f64::from(limit.stoss),
+            letzte_auffuellung: jetzt,
+        }
+    }
+
+    fn auffuellen(&mut self, limit: EimerLimit, jetzt: Instant) {
+        let vergangen = jetzt.saturating_duration_since(self.letzte_auffuellung);
+        self.token = (self.token + vergangen.as_secs_f64() * limit.pro_sekunde())
+            .min(f64::from(limit.stoss));
+        self.letzte_auffuellung = jetzt;
+    }
+
+    /// Verbraucht ein Token oder nennt die Wartezeit bis zum naechsten
+    fn verbrauchen(&mut self, limit: EimerLimit, jetzt: Instant) -> Result<(), Duration> {
+        self.auffuellen(limit, jetzt);
+        if self.token >= 1.0 {
+            self.token -= 1.0;
+            return Ok(());
+        }
+        // Ein Eimer ohne Fassungsvermoegen laesst nie etwas durch
+        if limit.stoss == 0 {
+            return Err(Duration::MAX);
+        }
+        Err(Duration::from_secs_f64(
+            (1.0 - self.token) / limit.pro_sekunde(),
+        ))
+    }
+}
+
+/// Ergebnis der Pruefung einer Nachricht
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Urteil {
+    /// Nachricht darf verarbeitet werden
+    Erlaubt,
+    /// Eimer leer; naechstes Token nach `warten`
+    Abgelehnt { warten: Duration },
+    /// Zu viele Ablehnungen: Verbindung trennen
+    Trennen { warten: Duration },
+}
+
+/// Ganze Sekunden fuer `retry_after_secs` (aufgerundet, mindestens 1)
+pub fn wiederholen_nach_sek(warten: Duration) -> u64 {
+    warten
+        .as_secs()
+        .saturating_add(u64::from(warten.subsec_nanos() > 0))
+        .max(1)
+}
+
+/// Drossel-Zustand einer Verbindung (im `DispatcherContext`)
+#[derive(Debug, Clone)]
+pub struct VerbindungsDrossel {
+    limits: DrosselLimits,
+    eimer: [Eimer; 5],
+    /// Zeitpunkte der Ablehnungen im Eskalationsfenster
+    ablehnungen: VecDeque<Instant>,
+    eskaliert: bool,
+}
+
+impl VerbindungsDrossel {
+    pub fn neu(limits: DrosselLimits) -> Self {
+        let jetzt = Instant::now();
+        let voll = |kategorie| Eimer::voll(limits.eimer(kategorie), jetzt);
+        Self {
+            limits,
+            eimer: [
+                voll(Kategorie::Anmeldung),
+                voll(Kategorie::Chat),
+                voll(Kategorie::Kanal),
+                voll(Kategorie::Ping),
+                voll(Kategorie::Sonstige),
+            ],
+            ablehnungen: VecDeque::new(),
+            eskaliert: false,
+        }
+    }
+
+    pub fn limits(&self) -> &DrosselLimits {
+        &self.limits
+    }
+
+    /// Verbucht eine Nachricht der Kategorie
+    pub fn pruefen(&mut self, kategorie: Kategorie) -> Urteil {
+        let limit = self.limits.eimer(kategorie);
+        if limit.unbegrenzt() {
+            return Urteil::Erlaubt;
+        }
+        let jetzt = Instant::now();
+        let warten = match self.eimer[kategorie.index()].verbrauchen(limit, jetzt) {
+            Ok(()) => return Urteil::Erlaubt,
+            Err(warten) => warten,
+        };
+
+        let fenster = self.limits.fenster;
+        while self
+            .ablehnungen
+            .front()
+            .is_some_and(|alt| jetzt.duration_since(*alt) >= fenster)
+        {
+            self.ablehnungen.pop_front();
+        }
+        self.ablehnungen.push_back(jetzt);
+
+        if self.limits.trennen_ab > 0 && self.ablehnungen.len() >= self.limits.trennen_ab {
+            self.eskaliert = true;
+            Urteil::Trennen { warten }
+        } else {
+            Urteil::Abgelehnt { warten }
+        }
+    }
+
+    /// Die Verbindung soll nach der laufenden Antwort getrennt werden
+    pub fn eskaliert(&self) -> bool {
+        self.eskaliert
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use speakeasy_protocol::control::{ChatDeleteRequest, PingMessage};
+
+    fn limits(chat: EimerLimit, trennen_ab: usize) -> DrosselLimits {
+        DrosselLimits {
+            chat,
+            trennen_ab,
+            fenster: Duration::from_secs(60),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn kategorien() {
+        let chat = ControlPayload::ChatDelete(ChatDeleteRequest {
+            message_id: String::new(),
+        });
+        assert_eq!(Kategorie::von(&chat), Some(Kategorie::Chat));
+        assert_eq!(
+            Kategorie::von(&ControlPayload::Ping(PingMessage { timestamp_ms: 0 })),
+            Some(Kategorie::Ping)
+        );
+        assert_eq!(
+            Kategorie::von(&ControlPayload::ClientList),
+            Some(Kategorie::Sonstige)
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn eimer_laeuft_anteilig_nach() {
+        // 30 pro Minute: alle 2 Sekunden ein Token, hoechstens 3 am Stueck
+        let mut drossel = VerbindungsDrossel::neu(limits(EimerLimit::neu(30, 3), 0));
+
+        for _ in 0..3 {
+            assert_eq!(drossel.pruefen(Kategorie::Chat), Urteil::Erlaubt);
+        }
+        assert_eq!(
+            drossel.pruefen(Kategorie::Chat),
+            Urteil::Abgelehnt {
+                warten: Duration::from_secs(2)
+            }
+        );
+
+        // Nach einer halben Sekunde fehlen noch 1,5 Sekunden
+        tokio::time::advance(Duration::from_millis(500)).await;
+        match drossel.pruefen(Kategorie::Chat) {
+            Urteil::Abgelehnt { warten } => {
+                assert_eq!(warten, Duration::from_millis(1500));
+                assert_eq!(wiederholen_nach_sek(warten), 2);
+            }
+            andere => panic!("unerwartet: {andere:?}"),
+        }
+
+        tokio::time::advance(Duration::from_millis(1500)).await;
+        assert_eq!(drossel.pruefen(Kategorie::Chat), Urteil::Erlaubt);
+        assert!(matches!(
+            drossel.pruefen(Kategorie::Chat),
+            Urteil::Abgelehnt { .. }
+        ));
+
+        // Lange Pause fuellt nur bis zum Fassungsvermoegen auf
+        tokio::time::advance(Duration::from_secs(600)).await;
+        for _ in 0..3 {
+            assert_eq!(drossel.pruefen(Kategorie::Chat), Urteil::Erlaubt);
+        }
+        assert!(matches!(
+            drossel.pruefen(Kategorie::Chat),
+            Urteil::Abgelehnt { .. }
+        ));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn kategorien_haben_getrennte_eimer() {
+        let mut drossel = VerbindungsDrossel::neu(limits(EimerLimit::neu(60, 1), 0));
+        assert_eq!(drossel.pruefen(Kategorie::Chat), Urteil::Erlaubt);
+        assert!(matches!(
+            drossel.pruefen(Kategorie::Chat),
+            Urteil::Abgelehnt { .. }
+        ));
+        assert_eq!(drossel.pruefen(Kategorie::Kanal), Urteil::Erlaubt);
+        assert_eq!(drossel.pruefen(Kategorie::Ping), Urteil::Erlaubt);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn unbegrenzter_eimer_zaehlt_nicht() {
+        let mut drossel = VerbindungsDrossel::neu(limits(EimerLimit::neu(0, 0), 1));
+        for _ in 0..1000 {
+            assert_eq!(drossel.pruefen(Kategorie::Chat), Urteil::Erlaubt);
+        }
+        assert!(!drossel.eskaliert());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn wiederholte_ablehnungen_eskalieren() {
+        let mut drossel = VerbindungsDrossel::neu(limits(EimerLimit::neu(6, 1), 3));
+        assert_eq!(drossel.pruefen(Kategorie::Chat), Urteil::Erlaubt);
+
+        // Zwei Ablehnungen, dann verlassen sie das Fenster
+        for _ in 0..2 {
+            assert!(matches!(
+                drossel.pruefen(Kategorie::Chat),
+                Urteil::Abgelehnt { .. }
+            ));
+        }
+        tokio::time::advance(Duration::from_secs(61)).await;
+        assert_eq!(drossel.pruefen(Kategorie::Chat), Urteil::Erlaubt);
+        assert!(!drossel.eskaliert());
+
+        // Drei Ablehnungen innerhalb des Fensters trennen die Verbindung
+        for _ in 0..2 {
+            assert!(matches!(
+                drossel.pruefen(Kategorie::Chat),
+                Urteil::Abgelehnt { .. }
+            ));
+            tokio::time::advance(Duration::from_secs(1)).await;
+        }
+        assert!(matches!(
+            drossel.pruefen(Kategorie::Chat),
+            Urteil::Trennen { .. }
+        ));
+        assert!(drossel.eskaliert());
+    }
+
+    #[test]
+    fn wartezeit_wird_aufgerundet() {
+        assert_eq!(wiederholen_nach_sek(Duration::ZERO), 1);
+        assert_eq!(wiederholen_nach_sek(Duration::from_millis(1)), 1);
+        assert_eq!(wiederholen_nach_sek(Duration::from_secs(2)), 2);
+        assert_eq!(wiederholen_nach_sek(Duration::from_millis(2001)), 3);
+        assert_eq!(wiederholen_nach_sek(Duration::MAX), u64::MAX);
+    }
+}
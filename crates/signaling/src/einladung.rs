@@ -0,0 +1,764 @@
+//! Einladungen und Beitrittsanfragen – soziale Wege in einen Kanal
+//!
+//! Zwei Richtungen, beide ohne Kanal-Passwort:
+//! - Einladung: ein Mitglied mit [`EINLADEN`] laedt einen Online-Benutzer
+//!   ein. Nimmt dieser an, tritt er sofort bei; Passwort und Freigabe
+//!   entfallen (die Kanal-Grenze gilt weiter).
+//! - Anklopfen: bei Kanaelen mit `join_by_approval` bittet ein Benutzer um
+//!   Einlass. Mitglieder mit [`BEITRITT_FREIGEBEN`] (nur bei ausdruecklichem
+//!   Grant) lassen ihn ein oder lehnen ab; Eingelassene erhalten einen
+//!   einmaligen Zutritt fuer den naechsten `ChannelJoin`.
+//!
+//! Offene Einladungen und Anfragen liegen nur im Speicher und verfallen nach
+//! [`EinladungsLimits::gueltigkeit`]; der Ausgang geht in jedem Fall an den
+//! Absender zurueck. Ein Ziel hat hoechstens `offen_pro_ziel` offene
+//! Einladungen, ein Kanal ebenso viele offene Anfragen, und jeder Absender
+//! ist je Zeitfenster begrenzt. Als soziale Aktionen landen beide Wege nicht
+//! im Audit-Log.
+
+use speakeasy_core::types::{ChannelId, UserId};
+use speakeasy_db::{
+    repository::UserRepository, BanRepository, ChannelRepository, ChatMessageRepository,
+    PermissionRepository, ServerGroupRepository,
+};
+use speakeasy_protocol::control::{
+    ChannelInviteEvent, ChannelInviteOutcome, ChannelInviteRequest, ChannelInviteResponse,
+    ChannelInviteResultEvent, ChannelKnockAnswerRequest, ChannelKnockEvent, ChannelKnockRequest,
+    ChannelKnockResponse, ChannelKnockResultEvent, ControlMessage, ControlPayload,
+};
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::time::Instant;
+use uuid::Uuid;
+
+use crate::error::{SignalingError, SignalingResult};
+use crate::notfall::ausdruecklich_gewaehrt;
+use crate::server_state::SignalingState;
+use crate::soundboard::wartezeit;
+
+/// Berechtigung zum Einladen in den eigenen Kanal
+pub const EINLADEN: &str = "b_channel_invite";
+/// Berechtigung zum Freigeben von Beitrittsanfragen (und Beitritt ohne Anfrage)
+pub const BEITRITT_FREIGEBEN: &str = "b_channel_join_approve";
+
+/// Laengste Begleitnachricht einer Einladung oder Anfrage (Zeichen)
+pub const MAX_NACHRICHT_ZEICHEN: usize = 200;
+
+/// Grenzen fuer Einladungen und Beitrittsanfragen
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EinladungsLimits {
+    /// So lange bleibt eine Einladung oder Anfrage offen
+    pub gueltigkeit: Duration,
+    /// Offene Einladungen je Ziel bzw. offene Anfragen je Kanal (0 = unbegrenzt)
+    pub offen_pro_ziel: usize,
+    /// Einladungen und Anfragen je Absender im Zeitfenster (0 = unbegrenzt)
+    pub pro_benutzer: usize,
+    /// Laenge des gleitenden Zeitfensters
+    pub fenster: Duration,
+}
+
+impl Default for EinladungsLimits {
+    fn default() -> Self {
+        Self {
+            gueltigkeit: Duration::from_secs(120),
+            offen_pro_ziel: 5,
+            pro_benutzer: 10,
+            fenster: Duration::from_secs(60),
+        }
+    }
+}
+
+/// Offene Einladung
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Einladung {
+    pub channel_id: ChannelId,
+    pub von: UserId,
+    pub an: UserId,
+    laeuft_ab: Instant,
+}
+
+/// Offene Beitrittsanfrage
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Anfrage {
+    pub channel_id: ChannelId,
+    pub von: UserId,
+    /// Benachrichtigte Freigebende (erhalten auch den Ausgang)
+    pub freigebende: Vec<UserId>,
+    laeuft_ab: Instant,
+}
+
+/// Ergebnis beim Einloesen einer Einladung oder Anfrage
+#[derive(Debug, PartialEq, Eq)]
+pub enum Eingeloest<T> {
+    Gueltig(T),
+    /// Verfallen, aber noch nicht abgeraeumt
+    Abgelaufen(T),
+    Unbekannt,
+}
+
+/// Offene Einladungen, Anfragen und gewaehrte Zutritte
+pub struct Einladungen {
+    limits: EinladungsLimits,
+    zustand: Mutex<Zustand>,
+}
+
+#[derive(Default)]
+struct Zustand {
+    einladungen: HashMap<String, Einladung>,
+    anfragen: HashMap<String, Anfrage>,
+    /// Einmaliger Zutritt nach angenommener Anfrage (bis Ablauf)
+    zutritte: HashMap<(UserId, ChannelId), Instant>,
+    versuche: HashMap<UserId, VecDeque<Instant>>,
+}
+
+impl Einladungen {
+    pub fn neu(limits: EinladungsLimits) -> Self {
+        Self {
+            limits,
+            zustand: Mutex::new(Zustand::default()),
+        }
+    }
+
+    /// Wie lange Einladungen und Anfragen offen bleiben
+    pub fn gueltigkeit(&self) -> Duration {
+        self.limits.gueltigkeit
+    }
+
+    fn zustand(&self) -> std::sync::MutexGuard<'_, Zustand> {
+        self.zustand.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Verbucht eine Einladung oder Anfrage des Absenders
+    fn drosseln(&self, zustand: &mut Zustand, von: UserId, jetzt: Instant) -> SignalingResult<()> {
+        let fenster = self.limits.fenster;
+        if let Some(warten) = wartezeit(
+            &mut zustand.versuche,
+            von,
+            self.limits.pro_benutzer,
+            fenster,
+            jetzt,
+        ) {
+            return Err(zu_haeufig(warten));
+        }
+        zustand.versuche.entry(von).or_default().push_back(jetzt);
+        zustand.versuche.retain(|_, z| {
+            z.back()
+                .is_some_and(|letzter| jetzt.duration_since(*letzter) < fenster)
+        });
+        Ok(())
+    }
+
+    /// Legt eine Einladung an und gibt ihre ID zurueck
+    ///
+    /// Eine noch offene Einladung desselben Absenders in denselben Kanal wird
+    /// wiederverwendet (`false` = nicht neu angelegt).
+    pub fn einladen(
+        &self,
+        channel_id: ChannelId,
+        von: UserId,
+        an: UserId,
+    ) -> SignalingResult<(String, bool)> {
+        let jetzt = Instant::now();
+        let mut zustand = self.zustand();
+        if let Some((id, _)) = zustand
+            .einladungen
+            .iter()
+            .find(|(_, e)| e.channel_id == channel_id && e.von == von && e.an == an)
+        {
+            return Ok((id.clone(), false));
+        }
+
+        let offen: Vec<Instant> = zustand
+            .einladungen
+            .values()
+            .filter(|e| e.an == an)
+            .map(|e| e.laeuft_ab)
+            .collect();
+        if let Some(warten) = ueber_grenze(&offen, self.limits.offen_pro_ziel, jetzt) {
+            return Err(zu_haeufig(warten));
+        }
+        self.drosseln(&mut zustand, von, jetzt)?;
+
+        let id = Uuid::new_v4().to_string();
+        zustand.einladungen.insert(
+            id.clone(),
+            Einladung {
+                channel_id,
+                von,
+                an,
+                laeuft_ab: jetzt + self.limits.gueltigkeit,
+            },
+        );
+        Ok((id, true))
+    }
+
+    /// Entfernt die Einladung `id`, sofern sie an `an` gerichtet ist
+    pub fn einladung_einloesen(&self, id: &str, an: UserId) -> Eingeloest<Einladung> {
+        let mut zustand = self.zustand();
+        match zustand.einladungen.get(id) {
+            Some(e) if e.an == an => {}
+            _ => return Eingeloest::Unbekannt,
+        }
+        let einladung = zustand.einladungen.remove(id).expect("eben gefunden");
+        if Instant::now() >= einladung.laeuft_ab {
+            Eingeloest::Abgelaufen(einladung)
+        } else {
+            Eingeloest::Gueltig(einladung)
+        }
+    }
+
+    /// Legt eine Beitrittsanfrage an und gibt ihre ID zurueck
+    ///
+    /// Wie bei [`Einladungen::einladen`] wird eine offene Anfrage desselben
+    /// Benutzers wiederverwendet.
+    pub fn anklopfen(
+        &self,
+        channel_id: ChannelId,
+        von: UserId,
+        freigebende: Vec<UserId>,
+    ) -> SignalingResult<(String, bool)> {
+        let jetzt = Instant::now();
+        let mut zustand = self.zustand();
+        if let Some((id, _)) = zustand
+            .anfragen
+            .iter()
+            .find(|(_, a)| a.channel_id == channel_id && a.von == von)
+        {
+            return Ok((id.clone(), false));
+        }
+
+        let offen: Vec<Instant> = zustand
+            .anfragen
+            .values()
+            .filter(|a| a.channel_id == channel_id)
+            .map(|a| a.laeuft_ab)
+            .collect();
+        if let Some(warten) = ueber_grenze(&offen, self.limits.offen_pro_ziel, jetzt) {
+            return Err(zu_haeufig(warten));
+        }
+        self.drosseln(&mut zustand, von, jetzt)?;
+
+        let id = Uuid::new_v4().to_string();
+        zustand.anfragen.insert(
+            id.clone(),
+            Anfrage {
+                channel_id,
+                von,
+                freigebende,
+                laeuft_ab: jetzt + self.limits.gueltigkeit,
+            },
+        );
+        Ok((id, true))
+    }
+
+    /// Offene Anfrage `id` (ohne sie zu entfernen)
+    pub fn anfrage(&self, id: &str) -> Option<Anfrage> {
+        self.zustand().anfragen.get(id).cloned()
+    }
+
+    /// Entfernt die Anfrage `id`
+    pub fn anfrage_einloesen(&self, id: &str) -> Eingeloest<Anfrage> {
+        let Some(anfrage) = self.zustand().anfragen.remove(id) else {
+            return Eingeloest::Unbekannt;
+        };
+        if Instant::now() >= anfrage.laeuft_ab {
+            Eingeloest::Abgelaufen(anfrage)
+        } else {
+            Eingeloest::Gueltig(anfrage)
+        }
+    }
+
+    /// Gewaehrt `user_id` einen einmaligen Beitritt ohne Passwort und Freigabe
+    pub fn zutritt_gewaehren(&self, user_id: UserId, channel_id: ChannelId) {
+        let jetzt = Instant::now();
+        let mut zustand = self.zustand();
+        zustand.zutritte.retain(|_, bis| *bis > jetzt);
+        zustand
+            .zutritte
+            .insert((user_id, channel_id), jetzt + self.limits.gueltigkeit);
+    }
+
+    /// Hat `user_id` einen gueltigen Zutritt fuer den Kanal?
+    pub fn hat_zutritt(&self, user_id: UserId, channel_id: ChannelId) -> bool {
+        self.zustand()
+            .zutritte
+            .get(&(user_id, channel_id))
+            .is_some_and(|bis| *bis > Instant::now())
+    }
+
+    /// Verbraucht den Zutritt nach einem erfolgreichen Beitritt
+    pub fn zutritt_einloesen(&self, user_id: UserId, channel_id: ChannelId) {
+        self.zustand().zutritte.remove(&(user_id, channel_id));
+    }
+
+    /// Entfernt eine Einladung nach Ablauf (`None` wenn schon beantwortet)
+    fn einladung_verfallen(&self, id: &str) -> Option<Einladung> {
+        self.zustand().einladungen.remove(id)
+    }
+
+    /// Entfernt eine Anfrage nach Ablauf (`None` wenn schon beantwortet)
+    fn anfrage_verfallen(&self, id: &str) -> Option<Anfrage> {
+        self.zustand().anfragen.remove(id)
+    }
+}
+
+/// Wartezeit, bis unter `grenze` offenen Eintraegen wieder Platz ist
+fn ueber_grenze(laeuft_ab: &[Instant], grenze: usize, jetzt: Instant) -> Option<Duration> {
+    if grenze == 0 || laeuft_ab.len() < grenze {
+        return None;
+    }
+    let mut zeiten = laeuft_ab.to_vec();
+    zeiten.sort();
+    Some(zeiten[laeuft_ab.len() - grenze].saturating_duration_since(jetzt))
+}
+
+fn zu_haeufig(warten: Duration) -> SignalingError {
+    SignalingError::ZuHaeufig {
+        retry_after_secs: warten.as_secs_f64().ceil().max(1.0) as u64,
+    }
+}
+
+/// Prueft die Laenge der Begleitnachricht
+fn nachricht_pruefen(nachricht: &Option<String>) -> SignalingResult<()> {
+    match nachricht {
+        Some(n) if n.chars().count() > MAX_NACHRICHT_ZEICHEN => Err(SignalingError::protokoll(
+            format!("Nachricht hoechstens {MAX_NACHRICHT_ZEICHEN} Zeichen"),
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// Anzeigename eines verbundenen Benutzers
+fn anzeigename<U, P, B>(state: &SignalingState<U, P, B>, user_id: &UserId) -> String
+where
+    U: UserRepository + ServerGroupRepository + ChannelRepository + ChatMessageRepository + 'static,
+    P: PermissionRepository + 'static,
+    B: BanRepository + 'static,
+{
+    state
+        .presence
+        .client_presence(user_id)
+        .map(|p| p.display_name)
+        .unwrap_or_else(|| user_id.to_string())
+}
+
+// ---------------------------------------------------------------------------
+// Einladungen
+// ---------------------------------------------------------------------------
+
+/// Laedt einen Online-Benutzer in den Kanal des Absenders ein
+///
+/// Das Ziel erhaelt `ChannelInviteReceived`. Bleibt die Einladung bis zum
+/// Ablauf unbeantwortet, erhaelt der Absender `ChannelInviteResult` mit
+/// `Expired`.
+pub async fn einladen<U, P, B>(
+    state: &Arc<SignalingState<U, P, B>>,
+    von: UserId,
+    anfrage: ChannelInviteRequest,
+) -> SignalingResult<ChannelInviteResponse>
+where
+    U: UserRepository + ServerGroupRepository + ChannelRepository + ChatMessageRepository + 'static,
+    P: PermissionRepository + 'static,
+    B: BanRepository + 'static,
+{
+    let channel_id = anfrage.channel_id;
+    let an = anfrage.target_user_id;
+    nachricht_pruefen(&anfrage.message)?;
+    if an == von {
+        return Err(SignalingError::protokoll("Selbst-Einladung nicht moeglich"));
+    }
+    if state.presence.channel_von_client(&von) != Some(channel_id) {
+        return Err(SignalingError::ZugriffVerweigert(
+            "Nur Mitglieder des Kanals koennen einladen".into(),
+        ));
+    }
+    match state
+        .permission_service
+        .berechtigung_pruefen(von.inner(), channel_id.inner(), EINLADEN)
+        .await
+    {
+        Ok(false) => {
+            return Err(SignalingError::ZugriffVerweigert(
+                "Keine Berechtigung zum Einladen".into(),
+            ))
+        }
+        Err(e) => tracing::error!("Berechtigungspruefung fehlgeschlagen: {}", e),
+        Ok(true) => {}
+    }
+    if !state.presence.ist_online(&an) {
+        return Err(SignalingError::NichtGefunden(format!(
+            "Benutzer {an} ist nicht verbunden"
+        )));
+    }
+    if state.presence.channel_von_client(&an) == Some(channel_id) {
+        return Err(SignalingError::protokoll("Benutzer ist bereits im Kanal"));
+    }
+    let kanal = ChannelRepository::get_by_id(state.db.as_ref(), channel_id.inner())
+        .await
+        .map_err(|e| SignalingError::intern(e.to_string()))?
+        .ok_or_else(|| SignalingError::NichtGefunden(format!("Kanal {channel_id}")))?;
+
+    let (invite_id, neu) = state.einladungen.einladen(channel_id, von, an)?;
+    let gueltigkeit = state.einladungen.gueltigkeit();
+    state.broadcaster.an_user_senden(
+        &an,
+        ControlMessage::new(
+            0,
+            ControlPayload::ChannelInviteReceived(ChannelInviteEvent {
+                invite_id: invite_id.clone(),
+                channel_id,
+                channel_name: kanal.name,
+                inviter_id: von,
+                inviter_name: anzeigename(state, &von),
+                message: anfrage.message,
+                expires_in_secs: gueltigkeit.as_secs(),
+            }),
+        ),
+    );
+    if neu {
+        let state = Arc::clone(state);
+        let id = invite_id.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(gueltigkeit).await;
+            if let Some(einladung) = state.einladungen.einladung_verfallen(&id) {
+                tracing::debug!(invite_id = %id, "Einladung verfallen");
+                einladung_ausgang_melden(&state, &id, &einladung, ChannelInviteOutcome::Expired);
+            }
+        });
+    }
+
+    tracing::debug!(von = %von, an = %an, channel_id = %channel_id, "Einladung verschickt");
+    Ok(ChannelInviteResponse {
+        invite_id,
+        expires_in_secs: gueltigkeit.as_secs(),
+    })
+}
+
+/// Meldet dem Einladenden den Ausgang und gibt das Event zurueck
+pub fn einladung_ausgang_melden<U, P, B>(
+    state: &SignalingState<U, P, B>,
+    invite_id: &str,
+    einladung: &Einladung,
+    outcome: ChannelInviteOutcome,
+) -> ChannelInviteResultEvent
+where
+    U: UserRepository + ServerGroupRepository + ChannelRepository + ChatMessageRepository + 'static,
+    P: PermissionRepository + 'static,
+    B: BanRepository + 'static,
+{
+    let event = ChannelInviteResultEvent {
+        invite_id: invite_id.to_string(),
+        channel_id: einladung.channel_id,
+        target_user_id: einladung.an,
+        outcome,
+    };
+    state.broadcaster.an_user_senden(
+        &einladung.von,
+        ControlMessage::new(0, ControlPayload::ChannelInviteResult(event.clone())),
+    );
+    event
+}
+
+// ---------------------------------------------------------------------------
+// Anklopfen
+// ---------------------------------------------------------------------------
+
+/// Bittet um Einlass in einen Kanal mit `join_by_approval`
+///
+/// Benachrichtigt alle Mitglieder des Kanals mit [`BEITRITT_FREIGEBEN`]; ist
+/// keines online, wird die Anfrage abgelehnt.
+pub async fn anklopfen<U, P, B>(
+    state: &Arc<SignalingState<U, P, B>>,
+    von: UserId,
+    anfrage: ChannelKnockRequest,
+) -> SignalingResult<ChannelKnockResponse>
+where
+    U: UserRepository + ServerGroupRepository + ChannelRepository + ChatMessageRepository + 'static,
+    P: PermissionRepository + 'static,
+    B: BanRepository + 'static,
+{
+    let channel_id = anfrage.channel_id;
+    nachricht_pruefen(&anfrage.message)?;
+    let kanal = ChannelRepository::get_by_id(state.db.as_ref(), channel_id.inner())
+        .await
+        .map_err(|e| SignalingError::intern(e.to_string()))?
+        .ok_or_else(|| SignalingError::NichtGefunden(format!("Kanal {channel_id}")))?;
+    if !kanal.join_by_approval {
+        return Err(SignalingError::protokoll(
+            "Kanal erfordert keine Freigabe, direkt beitreten",
+        ));
+    }
+    if state.presence.channel_von_client(&von) == Some(channel_id) {
+        return Err(SignalingError::protokoll("Bereits im Kanal"));
+    }
+    if let Ok(false) = state
+        .permission_service
+        .berechtigung_pruefen(von.inner(), channel_id.inner(), "b_channel_join")
+        .await
+    {
+        return Err(SignalingError::ZugriffVerweigert(
+            "Keine Berechtigung diesem Channel beizutreten".into(),
+        ));
+    }
+
+    let mut freigebende = Vec::new();
+    for mitglied in state.presence.user_ids_in_channel(&channel_id) {
+        if ausdruecklich_gewaehrt(state, mitglied, channel_id, BEITRITT_FREIGEBEN).await {
+            freigebende.push(mitglied);
+        }
+    }
+    if freigebende.is_empty() {
+        return Err(SignalingError::NichtGefunden(
+            "Niemand online, der den Beitritt freigeben kann".into(),
+        ));
+    }
+
+    let (knock_id, neu) = state
+        .einladungen
+        .anklopfen(channel_id, von, freigebende.clone())?;
+    let gueltigkeit = state.einladungen.gueltigkeit();
+    let event = ChannelKnockEvent {
+        knock_id: knock_id.clone(),
+        channel_id,
+        requester_id: von,
+        requester_name: anzeigename(state, &von),
+        message: anfrage.message,
+        expires_in_secs: gueltigkeit.as_secs(),
+    };
+    for freigebender in &freigebende {
+        state.broadcaster.an_user_senden(
+            freigebender,
+            ControlMessage::new(0, ControlPayload::ChannelKnockReceived(event.clone())),
+        );
+    }
+    if neu {
+        let state = Arc::clone(state);
+        let id = knock_id.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(gueltigkeit).await;
+            if let Some(anfrage) = state.einladungen.anfrage_verfallen(&id) {
+                tracing::debug!(knock_id = %id, "Beitrittsanfrage verfallen");
+                anfrage_ausgang_melden(&state, &id, &anfrage, ChannelInviteOutcome::Expired, None);
+            }
+        });
+    }
+
+    tracing::debug!(
+        von = %von,
+        channel_id = %channel_id,
+        freigebende = freigebende.len(),
+        "Beitrittsanfrage gestellt"
+    );
+    Ok(ChannelKnockResponse {
+        knock_id,
+        expires_in_secs: gueltigkeit.as_secs(),
+    })
+}
+
+/// Laesst einen Anfragenden ein oder lehnt ihn ab
+///
+/// Der Ausgang geht an den Anfragenden und die uebrigen Freigebenden; der
+/// Entscheidende erhaelt ihn als Antwort.
+pub async fn anfrage_beantworten<U, P, B>(
+    state: &Arc<SignalingState<U, P, B>>,
+    freigebender: UserId,
+    antwort: ChannelKnockAnswerRequest,
+) -> SignalingResult<ChannelKnockResultEvent>
+where
+    U: UserRepository + ServerGroupRepository + ChannelRepository + ChatMessageRepository + 'static,
+    P: PermissionRepository + 'static,
+    B: BanRepository + 'static,
+{
+    let unbekannt =
+        || SignalingError::NichtGefunden(format!("Beitrittsanfrage {}", antwort.knock_id));
+    let anfrage = state
+        .einladungen
+        .anfrage(&antwort.knock_id)
+        .ok_or_else(unbekannt)?;
+    if !ausdruecklich_gewaehrt(state, freigebender, anfrage.channel_id, BEITRITT_FREIGEBEN).await {
+        return Err(SignalingError::ZugriffVerweigert(
+            "Keine Berechtigung zum Freigeben".into(),
+        ));
+    }
+
+    let anfrage = match state.einladungen.anfrage_einloesen(&antwort.knock_id) {
+        Eingeloest::Gueltig(anfrage) => anfrage,
+        Eingeloest::Abgelaufen(anfrage) => {
+            anfrage_ausgang_melden(
+                state,
+                &antwort.knock_id,
+                &anfrage,
+                ChannelInviteOutcome::Expired,
+                None,
+            );
+            return Err(SignalingError::NichtGefunden(format!(
+                "Beitrittsanfrage {} ist abgelaufen",
+                antwort.knock_id
+            )));
+        }
+        Eingeloest::Unbekannt => return Err(unbekannt()),
+    };
+
+    let outcome = if antwort.admit {
+        state
+            .einladungen
+            .zutritt_gewaehren(anfrage.von, anfrage.channel_id);
+        ChannelInviteOutcome::Accepted
+    } else {
+        ChannelInviteOutcome::Declined
+    };
+    tracing::debug!(
+        freigebender = %freigebender,
+        von = %anfrage.von,
+        channel_id = %anfrage.channel_id,
+        eingelassen = antwort.admit,
+        "Beitrittsanfrage beantwortet"
+    );
+    Ok(anfrage_ausgang_melden(
+        state,
+        &antwort.knock_id,
+        &anfrage,
+        outcome,
+        Some(freigebender),
+    ))
+}
+
+/// Meldet den Ausgang einer Anfrage an alle Beteiligten ausser dem Entscheider
+fn anfrage_ausgang_melden<U, P, B>(
+    state: &SignalingState<U, P, B>,
+    knock_id: &str,
+    anfrage: &Anfrage,
+    outcome: ChannelInviteOutcome,
+    decided_by: Option<UserId>,
+) -> ChannelKnockResultEvent
+where
+    U: UserRepository + ServerGroupRepository + ChannelRepository + ChatMessageRepository + 'static,
+    P: PermissionRepository + 'static,
+    B: BanRepository + 'static,
+{
+    let event = ChannelKnockResultEvent {
+        knock_id: knock_id.to_string(),
+        channel_id: anfrage.channel_id,
+        requester_id: anfrage.von,
+        outcome,
+        decided_by,
+    };
+    let empfaenger = std::iter::once(&anfrage.von)
+        .chain(&anfrage.freigebende)
+        .filter(|u| Some(**u) != decided_by);
+    for user_id in empfaenger {
+        state.broadcaster.an_user_senden(
+            user_id,
+            ControlMessage::new(0, ControlPayload::ChannelKnockResult(event.clone())),
+        );
+    }
+    event
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn einladungen(offen_pro_ziel: usize, pro_benutzer: usize) -> Einladungen {
+        Einladungen::neu(EinladungsLimits {
+            gueltigkeit: Duration::from_secs(60),
+            offen_pro_ziel,
+            pro_benutzer,
+            fenster: Duration::from_secs(60),
+        })
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn einladung_verfaellt_nach_gueltigkeit() {
+        let e = einladungen(5, 10);
+        let (kanal, von, an) = (ChannelId::new(), UserId::new(), UserId::new());
+        let (id, neu) = e.einladen(kanal, von, an).unwrap();
+        assert!(neu);
+
+        tokio::time::advance(Duration::from_secs(61)).await;
+        match e.einladung_einloesen(&id, an) {
+            Eingeloest::Abgelaufen(einladung) => assert_eq!(einladung.von, von),
+            anders => panic!("Erwartet Abgelaufen, erhalten {anders:?}"),
+        }
+        assert_eq!(e.einladung_einloesen(&id, an), Eingeloest::Unbekannt);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn einladung_nur_fuer_das_ziel() {
+        let e = einladungen(5, 10);
+        let (kanal, von, an) = (ChannelId::new(), UserId::new(), UserId::new());
+        let (id, _) = e.einladen(kanal, von, an).unwrap();
+
+        assert_eq!(e.einladung_einloesen(&id, von), Eingeloest::Unbekannt);
+        assert!(matches!(
+            e.einladung_einloesen(&id, an),
+            Eingeloest::Gueltig(_)
+        ));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn offene_einladungen_je_ziel_begrenzt() {
+        let e = einladungen(2, 10);
+        let (kanal, an) = (ChannelId::new(), UserId::new());
+        e.einladen(kanal, UserId::new(), an).unwrap();
+        tokio::time::advance(Duration::from_secs(10)).await;
+        e.einladen(kanal, UserId::new(), an).unwrap();
+
+        // Die erste laeuft in 50 s ab, dann ist wieder Platz
+        let fehler = e.einladen(kanal, UserId::new(), an).unwrap_err();
+        assert!(matches!(
+            fehler,
+            SignalingError::ZuHaeufig {
+                retry_after_secs: 50
+            }
+        ));
+        // Andere Ziele sind nicht betroffen
+        e.einladen(kanal, UserId::new(), UserId::new()).unwrap();
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn wiederholte_einladung_wird_wiederverwendet() {
+        let e = einladungen(1, 1);
+        let (kanal, von, an) = (ChannelId::new(), UserId::new(), UserId::new());
+        let (erste, _) = e.einladen(kanal, von, an).unwrap();
+        let (zweite, neu) = e.einladen(kanal, von, an).unwrap();
+        assert_eq!(erste, zweite);
+        assert!(!neu);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn absender_im_zeitfenster_gedrosselt() {
+        let e = einladungen(0, 2);
+        let (kanal, von) = (ChannelId::new(), UserId::new());
+        e.einladen(kanal, von, UserId::new()).unwrap();
+        e.anklopfen(ChannelId::new(), von, Vec::new()).unwrap();
+        assert!(matches!(
+            e.einladen(kanal, von, UserId::new()),
+            Err(SignalingError::ZuHaeufig { .. })
+        ));
+
+        tokio::time::advance(Duration::from_secs(60)).await;
+        e.einladen(kanal, von, UserId::new()).unwrap();
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn zutritt_einmalig_und_befristet() {
+        let e = einladungen(5, 10);
+        let (kanal, user) = (ChannelId::new(), UserId::new());
+        assert!(!e.hat_zutritt(user, kanal));
+
+        e.zutritt_gewaehren(user, kanal);
+        assert!(e.hat_zutritt(user, kanal));
+        e.zutritt_einloesen(user, kanal);
+        assert!(!e.hat_zutritt(user, kanal));
+
+        e.zutritt_gewaehren(user, kanal);
+        tokio::time::advance(Duration::from_secs(61)).await;
+        assert!(!e.hat_zutritt(user, kanal));
+    }
+}
@@ -1,6 +1,7 @@
 //! Fehlertypen fuer den Signaling-Service
 
 use speakeasy_auth::AuthError;
+use speakeasy_core::SpeakeasyError;
 use thiserror::Error;
 
 /// Fehlertyp fuer den Signaling-Service
@@ -54,6 +55,10 @@ pub enum SignalingError {
     #[error("Timeout")]
     Timeout,
 
+    /// Zu viele Anfragen in kurzer Zeit
+    #[error("Zu haeufig, erneut in {retry_after_secs} s")]
+    ZuHaeufig { retry_after_secs: u64 },
+
     /// Interner Fehler
     #[error("Interner Fehler: {0}")]
     Intern(String),
@@ -73,3 +78,64 @@ impl SignalingError {
 
 /// Result-Typ fuer den Signaling-Service
 pub type SignalingResult<T> = Result<T, SignalingError>;
+
+impl From<SignalingError> for SpeakeasyError {
+    fn from(e: SignalingError) -> Self {
+        match e {
+            SignalingError::Io(io) => io.into(),
+            SignalingError::Auth(auth) => auth.into(),
+            SignalingError::VerbindungGetrennt | SignalingError::SendFehler => {
+                Self::Getrennt(e.to_string())
+            }
+            SignalingError::Protokoll(grund) => Self::UngueltigeNachricht(grund),
+            SignalingError::ZugriffVerweigert(grund) => Self::ZugriffVerweigert(grund),
+            SignalingError::NichtGefunden(was) => Self::NichtGefunden(was),
+            SignalingError::KanalVoll => Self::KanalVoll,
+            SignalingError::KanalPasswort => Self::KanalPasswort,
+            SignalingError::Gebannt(grund) => Self::Gebannt(grund),
+            SignalingError::ServerVoll => Self::ServerVoll,
+            SignalingError::Timeout => Self::Zeitlimit("Signaling".into()),
+            SignalingError::ZuHaeufig { retry_after_secs } => Self::RateLimit { retry_after_secs },
+            SignalingError::Intern(grund) => Self::Intern(grund),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use speakeasy_core::FehlerCode;
+
+    #[test]
+    fn jede_variante_hat_einen_fachlichen_code() {
+        let varianten = vec![
+            SignalingError::Io(std::io::Error::new(std::io::ErrorKind::BrokenPipe, "weg")),
+            SignalingError::Auth(AuthError::SessionAbgelaufen),
+            SignalingError::VerbindungGetrennt,
+            SignalingError::protokoll("falscher Zustand"),
+            SignalingError::ZugriffVerweigert("x".into()),
+            SignalingError::NichtGefunden("x".into()),
+            SignalingError::KanalVoll,
+            SignalingError::KanalPasswort,
+            SignalingError::Gebannt("x".into()),
+            SignalingError::ServerVoll,
+            SignalingError::SendFehler,
+            SignalingError::Timeout,
+            SignalingError::ZuHaeufig {
+                retry_after_secs: 3,
+            },
+        ];
+        for e in varianten {
+            let anzeige = e.to_string();
+            assert_ne!(
+                SpeakeasyError::from(e).code(),
+                FehlerCode::Intern,
+                "{anzeige}"
+            );
+        }
+        assert_eq!(
+            SpeakeasyError::from(SignalingError::intern("x")).code(),
+            FehlerCode::Intern
+        );
+    }
+}
@@ -1,29 +1,66 @@
-//! Auth-Handler – Login, Logout, Session-Validierung
+//! Auth-Handler – Hello, Registrierung, Login, Logout, Session-Validierung
 //!
 //! Verarbeitet alle auth-bezogenen ControlMessages und delegiert
 //! an den AuthService. Bei Erfolg wird die Session im Verbindungszustand
 //! gespeichert.
 
 use crate::error::SignalingResult;
-use crate::server_state::SignalingState;
+use crate::handlers::client_handler::client_info_aus_presence;
+use crate::server_state::{RegistrierungsModus, SignalingState};
+use speakeasy_auth::InviteService;
 use speakeasy_core::types::{ChannelId, UserId};
+use speakeasy_core::SpeakeasyError;
 use speakeasy_db::{
-    models::BenutzerUpdate,
+    models::{BanRecord, BenutzerUpdate, NeuerAuditEintrag},
     repository::UserRepository,
-    BanRepository, ChannelRepository, ChatMessageRepository, PermissionRepository,
-    ServerGroupRepository,
+    AuditLogRepository, BanRepository, ChannelRepository, ChatMessageRepository, InviteRepository,
+    PermissionRepository, ServerGroupRepository,
 };
 use speakeasy_protocol::control::{
-    ControlMessage, ControlPayload, ErrorCode, LoginRequest, LoginResponse, LogoutResponse,
-    NicknameChangeRequest, NicknameChangeResponse, PasswordChangeRequest, PasswordChangeResponse,
-    SetAwayRequest, SetAwayResponse,
+    ClientUpdatedEvent, ControlMessage, ControlPayload, ErrorCode, HelloRequest, LoginRequest,
+    LoginResponse, LogoutResponse, NicknameChangeRequest, NicknameChangeResponse,
+    PasswordChangeRequest, PasswordChangeResponse, ProtokollVersion, RegisterRequest,
+    RegisterResponse, SessionRenewResponse, SetAwayRequest, SetAwayResponse,
 };
+use speakeasy_protocol::handshake;
 use std::sync::Arc;
 
+/// Beantwortet die Begruessung vor dem Login mit `Welcome`
+///
+/// Clients einer anderen Major-Version erhalten einen
+/// `ProtokollVersion`-Fehler, danach trennt die Verbindung. Ob optionale
+/// Faehigkeiten fehlen, entscheidet der Client anhand der Antwort.
+pub fn handle_hello(request: HelloRequest, request_id: u32) -> ControlMessage {
+    tracing::debug!(
+        client = %request.client_name,
+        protokoll = %request.protocol_version,
+        "Hello empfangen"
+    );
+    match handshake::begruessen(&request, env!("CARGO_PKG_VERSION")) {
+        Ok(welcome) => ControlMessage::new(request_id, ControlPayload::Welcome(welcome)),
+        Err(inkompatibel) => {
+            tracing::info!(
+                client = %request.client_name,
+                protokoll = %request.protocol_version,
+                "Hello abgelehnt: andere Major-Version"
+            );
+            ControlMessage::fehler_mit_kontext(
+                request_id,
+                &inkompatibel.hinweis,
+                SpeakeasyError::ProtokollVersion {
+                    erwartet: ProtokollVersion::AKTUELL.major,
+                    erhalten: request.protocol_version.major,
+                },
+            )
+        }
+    }
+}
+
 /// Verarbeitet eine Login-Anfrage
 ///
 /// Prueft Credentials, erstellt eine Session und gibt LoginResponse zurueck.
-/// Ban-Pruefung findet VOR dem eigentlichen Login statt.
+/// Ban-Pruefung findet VOR dem eigentlichen Login statt: bei Passwort-Login
+/// fuer den Benutzer hinter dem Namen und die IP der TCP-Verbindung.
 pub async fn handle_login<U, P, B>(
     request: LoginRequest,
     request_id: u32,
@@ -35,21 +72,33 @@ where
     P: PermissionRepository + 'static,
     B: BanRepository + 'static,
 {
-    // Ban-Pruefung nach IP
-    match state.ban_service.ban_pruefen(None, Some(peer_ip)).await {
-        Err(speakeasy_auth::AuthError::IpGebannt(ip)) => {
-            tracing::warn!(ip = %ip, "Login von gebannter IP abgelehnt");
-            return ControlMessage::error(
-                request_id,
-                ErrorCode::Banned,
-                format!("IP gebannt: {}", ip),
+    // Ban-Pruefung nach Benutzer (per Name) und IP
+    let bekannte_id = if request.token.is_none() {
+        match UserRepository::get_by_name(state.db.as_ref(), &request.username).await {
+            Ok(benutzer) => benutzer.map(|b| b.id),
+            Err(e) => {
+                tracing::error!("Benutzer fuer Ban-Pruefung nicht ladbar: {}", e);
+                return ControlMessage::error(request_id, ErrorCode::InternalError, "Interner Fehler");
+            }
+        }
+    } else {
+        None
+    };
+    match state.ban_service.aktiver_ban(bekannte_id, Some(peer_ip)).await {
+        Ok(Some(ban)) => {
+            tracing::warn!(
+                ip = %peer_ip,
+                username = %request.username,
+                ban_id = %ban.id,
+                "Login eines gebannten Benutzers abgelehnt"
             );
+            return ban_abgelehnt(request_id, &ban);
         }
         Err(e) => {
             tracing::error!("Ban-Pruefung fehlgeschlagen: {}", e);
             return ControlMessage::error(request_id, ErrorCode::InternalError, "Interner Fehler");
         }
-        Ok(()) => {}
+        Ok(None) => {}
     }
 
     // Authentifizierung (Passwort oder API-Token)
@@ -127,21 +176,17 @@ where
         }
     };
 
-    // Ban-Pruefung nach User-ID
-    match state.ban_service.ban_pruefen(Some(benutzer.id), None).await {
-        Err(speakeasy_auth::AuthError::BenutzerGebannt(grund)) => {
+    // Ban-Pruefung nach User-ID (Token-Login kennt den Benutzer erst jetzt)
+    match state.ban_service.aktiver_ban(Some(benutzer.id), None).await {
+        Ok(Some(ban)) => {
             // Session sofort wieder invalidieren
             let _ = state.auth_service.abmelden(&session.token).await;
-            return ControlMessage::error(
-                request_id,
-                ErrorCode::Banned,
-                format!("Gebannt: {}", grund),
-            );
+            return ban_abgelehnt(request_id, &ban);
         }
         Err(e) => {
             tracing::error!("Ban-Pruefung fehlgeschlagen: {}", e);
         }
-        Ok(()) => {}
+        Ok(None) => {}
     }
 
     // Ablaufzeit berechnen (chrono DateTime -> Unix-Timestamp)
@@ -172,6 +217,9 @@ where
         is_output_muted: false,
         is_away: false,
         away_message: None,
+        nur_hoeren: false,
+        nur_hoeren_gewuenscht: false,
+        ip: Some(peer_ip.to_string()),
     });
 
     // Auto-Join: User automatisch in Default-Channel bewegen
@@ -179,6 +227,9 @@ where
         let channel_id = ChannelId(default_channel.id);
         state.presence.channel_beitreten(user_id, channel_id);
         state.broadcaster.channel_beitreten(user_id, channel_id);
+        crate::handlers::voice_handler::sendemodus_anwenden(state, user_id, channel_id, None)
+            .await;
+        crate::notfall::beim_beitritt(state, user_id, channel_id).await;
         tracing::debug!(
             user_id = %user_id,
             channel_id = %channel_id,
@@ -205,6 +256,162 @@ where
             expires_at,
             server_groups,
             must_change_password,
+            welcome_message: state.einstellungen.aktuell().willkommensnachricht,
+            motd: state.einstellungen.motd(),
+        }),
+    )
+}
+
+/// Fehlerantwort fuer einen aktiven Ban
+///
+/// Die Details tragen zusaetzlich `reason` und `expires_at` (Unix-Sekunden,
+/// `null` bei permanentem Ban).
+pub(crate) fn ban_abgelehnt(request_id: u32, ban: &BanRecord) -> ControlMessage {
+    let mut antwort =
+        ControlMessage::fehler(request_id, SpeakeasyError::Gebannt(ban.reason.clone()));
+    if let ControlPayload::Error(ref mut fehler) = antwort.payload {
+        if let Some(details) = fehler.details.as_mut() {
+            details["reason"] = ban.reason.clone().into();
+            details["expires_at"] = ban.expires_at.map(|t| t.timestamp()).into();
+        }
+    }
+    antwort
+}
+
+/// Verarbeitet eine Registrierung vor dem Login
+///
+/// Prueft Registrierungsmodus und IP-Ban, verbraucht die Einladung (falls
+/// angegeben oder verlangt) und legt dann das Konto an. Scheitert das Anlegen,
+/// etwa an einem vergebenen Namen, geht die Nutzung an die Einladung zurueck.
+/// Die Gruppe der Einladung wird dem neuen Konto zugewiesen.
+pub async fn handle_register<U, P, B>(
+    request: RegisterRequest,
+    request_id: u32,
+    peer_ip: &str,
+    state: &Arc<SignalingState<U, P, B>>,
+) -> ControlMessage
+where
+    U: UserRepository
+        + ServerGroupRepository
+        + ChannelRepository
+        + ChatMessageRepository
+        + InviteRepository
+        + AuditLogRepository
+        + 'static,
+    P: PermissionRepository + 'static,
+    B: BanRepository + 'static,
+{
+    let code = request
+        .invite_code
+        .as_deref()
+        .map(str::trim)
+        .filter(|c| !c.is_empty());
+    match (state.config.registrierung, code) {
+        (RegistrierungsModus::Geschlossen, _) => {
+            return ControlMessage::fehler(
+                request_id,
+                SpeakeasyError::RegistrierungDeaktiviert(
+                    "Der Server nimmt keine neuen Konten an".into(),
+                ),
+            );
+        }
+        (RegistrierungsModus::NurEinladung, None) => {
+            return ControlMessage::fehler(
+                request_id,
+                SpeakeasyError::EinladungUngueltig("Einladungscode erforderlich".into()),
+            );
+        }
+        _ => {}
+    }
+
+    let username = request.username.trim();
+    if username.is_empty() || request.password.is_empty() {
+        return ControlMessage::error(
+            request_id,
+            ErrorCode::InvalidRequest,
+            "Benutzername und Passwort duerfen nicht leer sein",
+        );
+    }
+
+    match state.ban_service.aktiver_ban(None, Some(peer_ip)).await {
+        Ok(Some(ban)) => {
+            tracing::warn!(ip = %peer_ip, ban_id = %ban.id, "Registrierung von gebannter IP abgelehnt");
+            return ban_abgelehnt(request_id, &ban);
+        }
+        Err(e) => {
+            tracing::error!("Ban-Pruefung fehlgeschlagen: {}", e);
+            return ControlMessage::error(request_id, ErrorCode::InternalError, "Interner Fehler");
+        }
+        Ok(None) => {}
+    }
+
+    // Nutzung vor dem Anlegen verbrauchen: bei parallelen Einloesungen
+    // desselben Codes gewinnt genau eine, die anderen legen kein Konto an
+    let einladungen = InviteService::neu(
+        Arc::clone(&state.db),
+        Arc::clone(&state.db),
+        Arc::clone(&state.db),
+    );
+    let einladung = match code {
+        Some(code) => match einladungen.einladung_reservieren(code).await {
+            Ok(einladung) => Some(einladung),
+            Err(e) => {
+                tracing::debug!(fehler = %e, "Einladung bei Registrierung abgelehnt");
+                return ControlMessage::fehler(request_id, SpeakeasyError::from(e));
+            }
+        },
+        None => None,
+    };
+
+    let benutzer = match state
+        .auth_service
+        .registrieren(username, &request.password)
+        .await
+    {
+        Ok(benutzer) => benutzer,
+        Err(e) => {
+            tracing::debug!(username = %username, fehler = %e, "Registrierung abgelehnt");
+            if let Some(einladung) = &einladung {
+                if let Err(freigabe) = einladungen.reservierung_freigeben(&einladung.code).await {
+                    tracing::warn!(fehler = %freigabe, "Einladungsnutzung nicht zurueckgegeben");
+                }
+            }
+            return ControlMessage::fehler(request_id, SpeakeasyError::from(e));
+        }
+    };
+    if let Some(einladung) = &einladung {
+        einladungen.gruppe_zuweisen(einladung, benutzer.id).await;
+    }
+
+    let server_groups = match state.db.list_for_user(benutzer.id).await {
+        Ok(gruppen) => gruppen.into_iter().map(|g| g.name).collect(),
+        Err(e) => {
+            tracing::warn!(
+                user_id = %benutzer.id,
+                fehler = %e,
+                "Server-Gruppen konnten nicht geladen werden"
+            );
+            vec![]
+        }
+    };
+
+    state
+        .audit_protokollieren(NeuerAuditEintrag::neu(
+            Some(benutzer.id),
+            "benutzer.registriert",
+            Some("user"),
+            Some(&benutzer.id.to_string()),
+            serde_json::json!({ "einladung": code, "ip": peer_ip }),
+        ))
+        .await;
+
+    ControlMessage::new(
+        request_id,
+        ControlPayload::RegisterResponse(RegisterResponse {
+            user_id: UserId(benutzer.id),
+            username: benutzer.username,
+            display_name: request.display_name,
+            server_groups,
         }),
     )
 }
@@ -239,6 +446,38 @@ where
     }
 }
 
+/// Verlaengert die Session der Verbindung (gleitender Ablauf)
+///
+/// Hat der Cleanup-Task die Session zwischen Warnung und Anfrage bereits
+/// entfernt, antwortet der Server mit `SessionExpired`; der Client muss sich
+/// dann neu anmelden.
+pub async fn handle_session_renew<U, P, B>(
+    session_token: &str,
+    request_id: u32,
+    state: &Arc<SignalingState<U, P, B>>,
+) -> ControlMessage
+where
+    U: UserRepository + ServerGroupRepository + ChannelRepository + ChatMessageRepository + 'static,
+    P: PermissionRepository + 'static,
+    B: BanRepository + 'static,
+{
+    match state.auth_service.session_verlaengern(session_token).await {
+        Ok(session) => {
+            tracing::debug!(user_id = %session.user_id, "Session verlaengert");
+            ControlMessage::new(
+                request_id,
+                ControlPayload::SessionRenewResponse(SessionRenewResponse {
+                    expires_at: session.laeuft_ab_am.timestamp() as u64,
+                }),
+            )
+        }
+        Err(e) => {
+            tracing::info!(fehler = %e, "Session-Verlaengerung abgelehnt");
+            ControlMessage::fehler(request_id, SpeakeasyError::from(e))
+        }
+    }
+}
+
 /// Verarbeitet eine Passwort-Aenderungs-Anfrage
 pub async fn handle_password_change<U, P, B>(
     request: PasswordChangeRequest,
@@ -324,6 +563,13 @@ where
         Ok(_) => {
             // Presence-Anzeigename aktualisieren und Broadcast
             state.presence.nickname_aktualisieren(user_id, nickname.clone());
+            if let Some(presence) = state.presence.client_presence(&user_id) {
+                let client = client_info_aus_presence(&presence, &state.voice_state);
+                state.broadcaster.an_alle_senden(ControlMessage::new(
+                    0,
+                    ControlPayload::ClientUpdated(ClientUpdatedEvent { client }),
+                ));
+            }
 
             tracing::info!(
                 user_id = %user_id,
@@ -383,3 +629,191 @@ where
     let benutzer = state.auth_service.session_validieren(token).await?;
     Ok(UserId(benutzer.id))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::server_state::SignalingConfig;
+    use crate::test_hilfen::{TestAufbau, TestState};
+    use speakeasy_core::FehlerCode;
+    use speakeasy_db::models::{NeueEinladung, NeueServerGruppe};
+
+    async fn state_mit(registrierung: RegistrierungsModus) -> TestState {
+        TestAufbau::default()
+            .config(SignalingConfig {
+                registrierung,
+                ..Default::default()
+            })
+            .bauen()
+            .await
+    }
+
+    async fn einladung(state: &TestState, max_uses: i64, gruppe: Option<uuid::Uuid>) -> String {
+        let admin = state
+            .auth_service
+            .registrieren("admin", "admin-passwort")
+            .await
+            .unwrap();
+        InviteRepository::create(
+            state.db.as_ref(),
+            NeueEinladung {
+                code: "EINMAL42",
+                channel_id: None,
+                assigned_group_id: gruppe,
+                max_uses,
+                expires_at: None,
+                created_by: admin.id,
+            },
+        )
+        .await
+        .unwrap();
+        "EINMAL42".into()
+    }
+
+    fn anfrage(username: &str, code: Option<&str>) -> RegisterRequest {
+        RegisterRequest {
+            username: username.into(),
+            password: "sicheres-passwort".into(),
+            invite_code: code.map(Into::into),
+            display_name: None,
+        }
+    }
+
+    fn fehler_code(antwort: &ControlMessage) -> FehlerCode {
+        match &antwort.payload {
+            ControlPayload::Error(fehler) => fehler.fehler_code(),
+            andere => panic!("Fehlerantwort erwartet, erhalten: {andere:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn einmal_einladung_wird_parallel_nur_einmal_eingeloest() {
+        let state = state_mit(RegistrierungsModus::NurEinladung).await;
+        let code = einladung(&state, 1, None).await;
+
+        let (erste, zweite) = tokio::join!(
+            handle_register(anfrage("carla", Some(&code)), 1, "10.0.0.1", &state),
+            handle_register(anfrage("dora", Some(&code)), 2, "10.0.0.2", &state),
+        );
+
+        let antworten = [&erste, &zweite];
+        let erfolge: Vec<_> = antworten
+            .iter()
+            .filter_map(|a| match &a.payload {
+                ControlPayload::RegisterResponse(r) => Some(r.username.clone()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(erfolge.len(), 1, "genau eine Einloesung: {antworten:?}");
+        let verlierer = antworten
+            .iter()
+            .find(|a| matches!(a.payload, ControlPayload::Error(_)))
+            .unwrap();
+        assert_eq!(fehler_code(verlierer), FehlerCode::EinladungUngueltig);
+
+        // Der Verlierer hat kein Konto angelegt
+        let verlierer_name = if erfolge[0] == "carla" {
+            "dora"
+        } else {
+            "carla"
+        };
+        assert!(
+            UserRepository::get_by_name(state.db.as_ref(), verlierer_name)
+                .await
+                .unwrap()
+                .is_none()
+        );
+        let gespeichert = InviteRepository::get_by_code(state.db.as_ref(), &code)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(gespeichert.used_count, 1);
+    }
+
+    #[tokio::test]
+    async fn deaktivierte_registrierung_und_ungueltiger_code_unterscheiden_sich() {
+        let geschlossen = state_mit(RegistrierungsModus::Geschlossen).await;
+        let antwort = handle_register(anfrage("carla", None), 1, "10.0.0.1", &geschlossen).await;
+        assert_eq!(fehler_code(&antwort), FehlerCode::RegistrierungDeaktiviert);
+
+        let nur_einladung = state_mit(RegistrierungsModus::NurEinladung).await;
+        let ohne_code =
+            handle_register(anfrage("carla", None), 2, "10.0.0.1", &nur_einladung).await;
+        assert_eq!(fehler_code(&ohne_code), FehlerCode::EinladungUngueltig);
+        let falscher_code = handle_register(
+            anfrage("carla", Some("GIBTSNICHT")),
+            3,
+            "10.0.0.1",
+            &nur_einladung,
+        )
+        .await;
+        assert_eq!(fehler_code(&falscher_code), FehlerCode::EinladungUngueltig);
+        assert!(
+            UserRepository::get_by_name(nur_einladung.db.as_ref(), "carla")
+                .await
+                .unwrap()
+                .is_none()
+        );
+
+        let offen = state_mit(RegistrierungsModus::Offen).await;
+        let antwort = handle_register(anfrage("carla", None), 4, "10.0.0.1", &offen).await;
+        assert!(matches!(
+            antwort.payload,
+            ControlPayload::RegisterResponse(_)
+        ));
+    }
+
+    #[tokio::test]
+    async fn vergebener_name_gibt_einladung_zurueck() {
+        let state = state_mit(RegistrierungsModus::NurEinladung).await;
+        let code = einladung(&state, 1, None).await;
+
+        let antwort = handle_register(anfrage("admin", Some(&code)), 1, "10.0.0.1", &state).await;
+        assert_eq!(fehler_code(&antwort), FehlerCode::BenutzernameVergeben);
+
+        let antwort = handle_register(anfrage("carla", Some(&code)), 2, "10.0.0.1", &state).await;
+        assert!(matches!(
+            antwort.payload,
+            ControlPayload::RegisterResponse(_)
+        ));
+    }
+
+    #[tokio::test]
+    async fn einladung_weist_server_gruppe_zu() {
+        let state = state_mit(RegistrierungsModus::NurEinladung).await;
+        let gruppe = ServerGroupRepository::create(
+            state.db.as_ref(),
+            NeueServerGruppe {
+                name: "Mitglieder",
+                priority: 10,
+                is_default: false,
+            },
+        )
+        .await
+        .unwrap();
+        let code = einladung(&state, 0, Some(gruppe.id)).await;
+
+        let antwort = handle_register(
+            RegisterRequest {
+                display_name: Some("Carla".into()),
+                ..anfrage("carla", Some(&code))
+            },
+            1,
+            "10.0.0.1",
+            &state,
+        )
+        .await;
+        let ControlPayload::RegisterResponse(antwort) = antwort.payload else {
+            panic!("RegisterResponse erwartet: {antwort:?}");
+        };
+        assert_eq!(antwort.display_name.as_deref(), Some("Carla"));
+        assert!(antwort.server_groups.contains(&"Mitglieder".to_string()));
+
+        // Mit dem neuen Konto ist ein normaler Login moeglich
+        assert!(state
+            .auth_service
+            .anmelden("carla", "sicheres-passwort")
+            .await
+            .is_ok());
+    }
+}
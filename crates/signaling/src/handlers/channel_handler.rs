@@ -6,25 +6,39 @@
 
 use speakeasy_core::types::{ChannelId, UserId};
 use speakeasy_db::{
-    models::{KanalTyp, KanalUpdate, NeuerKanal},
+    models::{
+        BerechtigungsWert, KanalTyp, KanalUpdate, NeuerKanal, TriState, MAX_LANGSAMMODUS_SEK,
+    },
     repository::UserRepository,
-    BanRepository, ChannelRepository, ChatMessageRepository, PermissionRepository,
-    ServerGroupRepository,
+    AuditLogRepository, BanRepository, ChannelRepository, ChatMessageRepository,
+    PermissionRepository, ServerGroupRepository,
 };
 use speakeasy_protocol::control::{
     ChannelCreateRequest, ChannelCreateResponse, ChannelDeleteRequest, ChannelEditRequest,
-    ChannelInfo, ChannelJoinRequest, ChannelJoinResponse, ChannelLeaveRequest, ChannelListResponse,
-    ClientInfo, ControlMessage, ControlPayload, ErrorCode,
+    ChannelEditedEvent, ChannelEmergencyMuteRequest, ChannelInfo, ChannelInviteAnswerRequest,
+    ChannelInviteOutcome, ChannelInviteRequest, ChannelJoinRequest, ChannelKnockAnswerRequest,
+    ChannelKnockRequest, ChannelLeaveRequest, ChannelListRequest, ChannelListResponse,
+    ChannelMembersRequest, ChannelTreeExpandRequest, ControlMessage, ControlPayload, ErrorCode,
+    SoundboardPlayRequest, SoundboardStopRequest,
 };
 use std::sync::Arc;
 
-use crate::presence::ClientPresence;
+use crate::bearbeitungsfrist::wirksame_frist;
+use crate::einladung::{Eingeloest, BEITRITT_FREIGEBEN};
+use crate::error::{SignalingError, SignalingResult};
+use crate::handlers::voice_handler::{sendemodus_anwenden, ssrc_melden};
+use crate::kanalbaum::{Kanalbaum, MAX_TEILBAUM_TIEFE};
+use crate::langsammodus::intervall_laden;
 use crate::server_state::SignalingState;
 
 /// Erstellt ChannelInfo aus einem DB-KanalRecord
+///
+/// `standard_frist` ist die Bearbeitungsfrist des Servers fuer Kanaele ohne
+/// eigene Frist.
 fn channel_info_aus_record(
     record: &speakeasy_db::models::KanalRecord,
     client_anzahl: u32,
+    standard_frist: u32,
 ) -> ChannelInfo {
     ChannelInfo {
         channel_id: ChannelId(record.id),
@@ -41,11 +55,21 @@ fn channel_info_aus_record(
         password_protected: record.password_hash.is_some(),
         codec: "opus".to_string(),
         codec_quality: 7,
+        // Wird beim Ausliefern aus dem Kanalbaum gesetzt
+        has_children: false,
+        child_count: 0,
+        slow_mode_secs: record.slow_mode_secs.clamp(0, MAX_LANGSAMMODUS_SEK as i64) as u32,
+        edit_window_secs: wirksame_frist(record.edit_window_secs, standard_frist),
+        join_by_approval: record.join_by_approval,
     }
 }
 
 /// Erstellt ChannelInfo fuer einen ephemeren Channel (nicht in DB)
-fn channel_info_ephemer(channel_id: ChannelId, client_anzahl: u32) -> ChannelInfo {
+fn channel_info_ephemer(
+    channel_id: ChannelId,
+    client_anzahl: u32,
+    standard_frist: u32,
+) -> ChannelInfo {
     ChannelInfo {
         channel_id,
         name: format!("Channel {}", &channel_id.inner().to_string()[..8]),
@@ -57,33 +81,23 @@ fn channel_info_ephemer(channel_id: ChannelId, client_anzahl: u32) -> ChannelInf
         password_protected: false,
         codec: "opus".to_string(),
         codec_quality: 7,
+        has_children: false,
+        child_count: 0,
+        slow_mode_secs: 0,
+        edit_window_secs: standard_frist,
+        join_by_approval: false,
     }
 }
 
-/// Konvertiert ClientPresence in ClientInfo fuer Protokoll-Antworten
-fn client_info_aus_presence(presence: &ClientPresence) -> ClientInfo {
-    ClientInfo {
-        user_id: presence.user_id,
-        username: presence.username.clone(),
-        display_name: presence.display_name.clone(),
-        channel_id: presence.channel_id,
-        server_groups: vec![],
-        is_muted: presence.is_output_muted,
-        is_deafened: presence.is_output_muted,
-        is_input_muted: presence.is_input_muted,
-    }
-}
-
-/// Verarbeitet Channel-Listen-Anfrage
-pub async fn handle_channel_list<U, P, B>(
-    request_id: u32,
-    state: &Arc<SignalingState<U, P, B>>,
-) -> ControlMessage
+/// Laedt alle Kanaele (DB und ephemere Voice-Channels) als Baum
+async fn kanalbaum_laden<U, P, B>(state: &Arc<SignalingState<U, P, B>>) -> Kanalbaum
 where
     U: UserRepository + ServerGroupRepository + ChannelRepository + ChatMessageRepository + 'static,
     P: PermissionRepository + 'static,
     B: BanRepository + 'static,
 {
+    let standard_frist = state.einstellungen.aktuell().chat_bearbeitungsfrist_sek;
+
     // DB-Channels laden
     let mut channels: Vec<ChannelInfo> = match ChannelRepository::list(state.db.as_ref()).await {
         Ok(db_channels) => db_channels
@@ -91,7 +105,7 @@ where
             .map(|record| {
                 let cid = ChannelId(record.id);
                 let anzahl = state.presence.user_ids_in_channel(&cid).len() as u32;
-                channel_info_aus_record(record, anzahl)
+                channel_info_aus_record(record, anzahl, standard_frist)
             })
             .collect(),
         Err(e) => {
@@ -107,16 +121,198 @@ where
     for cid in aktive_kanaele {
         if !db_channel_ids.contains(&cid) {
             let anzahl = state.presence.user_ids_in_channel(&cid).len() as u32;
-            channels.push(channel_info_ephemer(cid, anzahl));
+            channels.push(channel_info_ephemer(cid, anzahl, standard_frist));
         }
     }
 
+    Kanalbaum::neu(channels)
+}
+
+/// Gibt `true` zurueck wenn der Baum nur teilweise ausgeliefert wird
+fn nur_teilweise<U, P, B>(baum: &Kanalbaum, state: &SignalingState<U, P, B>) -> bool
+where
+    U: UserRepository + ServerGroupRepository + ChannelRepository + ChatMessageRepository + 'static,
+    P: PermissionRepository + 'static,
+    B: BanRepository + 'static,
+{
+    let schwelle = state.config.kanalbaum_teilweise_ab;
+    schwelle > 0 && baum.len() > schwelle
+}
+
+/// Verarbeitet Channel-Listen-Anfrage
+///
+/// Ohne Parameter wird der ganze Baum geliefert, oberhalb der Schwelle nur
+/// Root-Ebene und Pfad zum Kanal des Clients.
+pub async fn handle_channel_list<U, P, B>(
+    request: ChannelListRequest,
+    request_id: u32,
+    user_id: UserId,
+    state: &Arc<SignalingState<U, P, B>>,
+) -> ControlMessage
+where
+    U: UserRepository + ServerGroupRepository + ChannelRepository + ChatMessageRepository + 'static,
+    P: PermissionRepository + 'static,
+    B: BanRepository + 'static,
+{
+    let baum = kanalbaum_laden(state).await;
+    let teilweise = nur_teilweise(&baum, state);
+
+    if let Some(parent_id) = request.parent_id {
+        if !baum.enthaelt(&parent_id) {
+            return ControlMessage::error(
+                request_id,
+                ErrorCode::NotFound,
+                "Channel nicht gefunden",
+            );
+        }
+    }
+
+    let (channels, partial) = match (request.parent_id, request.depth) {
+        (None, None) if !teilweise => (baum.vollstaendig(), false),
+        (None, None) => {
+            let eigener_kanal = state.presence.channel_von_client(&user_id);
+            (baum.snapshot(eigener_kanal), true)
+        }
+        (parent_id, depth) => {
+            let standard = if teilweise { 1 } else { u32::MAX };
+            let tiefe = begrenzte_tiefe(depth.unwrap_or(standard), teilweise);
+            (baum.teilbaum(parent_id, tiefe), true)
+        }
+    };
+
+    ControlMessage::new(
+        request_id,
+        ControlPayload::ChannelListResponse(ChannelListResponse { channels, partial }),
+    )
+}
+
+/// Verarbeitet das Nachladen eines Teilbaums
+pub async fn handle_channel_tree_expand<U, P, B>(
+    request: ChannelTreeExpandRequest,
+    request_id: u32,
+    state: &Arc<SignalingState<U, P, B>>,
+) -> ControlMessage
+where
+    U: UserRepository + ServerGroupRepository + ChannelRepository + ChatMessageRepository + 'static,
+    P: PermissionRepository + 'static,
+    B: BanRepository + 'static,
+{
+    let baum = kanalbaum_laden(state).await;
+    if !baum.enthaelt(&request.channel_id) {
+        return ControlMessage::error(request_id, ErrorCode::NotFound, "Channel nicht gefunden");
+    }
+
+    let tiefe = begrenzte_tiefe(request.depth.unwrap_or(1), nur_teilweise(&baum, state));
     ControlMessage::new(
         request_id,
-        ControlPayload::ChannelListResponse(ChannelListResponse { channels }),
+        ControlPayload::ChannelListResponse(ChannelListResponse {
+            channels: baum.teilbaum(Some(request.channel_id), tiefe),
+            partial: true,
+        }),
     )
 }
 
+/// Argon2-Hash eines Kanal-Passworts; leer oder fehlend ergibt `None`
+fn kanal_passwort_hashen(passwort: Option<&str>) -> SignalingResult<Option<String>> {
+    match passwort.filter(|p| !p.is_empty()) {
+        Some(p) => Ok(Some(speakeasy_auth::passwort_hashen(p)?)),
+        None => Ok(None),
+    }
+}
+
+/// Begrenzt die angefragte Tiefe oberhalb der Schwelle
+fn begrenzte_tiefe(tiefe: u32, teilweise: bool) -> u32 {
+    if teilweise {
+        tiefe.clamp(1, MAX_TEILBAUM_TIEFE)
+    } else {
+        tiefe
+    }
+}
+
+/// Erlaubt den Beitritt ohne Kanal-Passwort
+pub const PASSWORT_AUSNAHME: &str = "b_channel_join_ignore_password";
+/// Erlaubt den Beitritt in volle Kanaele
+pub const LIMIT_AUSNAHME: &str = "b_channel_join_ignore_maxclients";
+
+/// Prueft Passwort, Freigabe und Belegung vor einem Kanalbeitritt
+///
+/// Beide Pruefungen entfallen fuer ausdruecklich gewaehrte Ausnahmen
+/// ([`PASSWORT_AUSNAHME`], [`LIMIT_AUSNAHME`]). Kanaele mit
+/// `join_by_approval` verlangen eine angenommene Einladung oder Anfrage
+/// (siehe [`crate::einladung`]) oder ein ausdrueckliches
+/// [`BEITRITT_FREIGEBEN`]; ein solcher Zutritt ersetzt auch das Passwort.
+/// Wer schon im Kanal ist, wird nicht erneut geprueft. Die Belegung wird
+/// zuletzt und ohne weiteres `await` geprueft; der Aufrufer tritt direkt
+/// danach bei.
+async fn beitritt_pruefen<U, P, B>(
+    state: &Arc<SignalingState<U, P, B>>,
+    user_id: UserId,
+    channel_id: ChannelId,
+    passwort: Option<&str>,
+) -> SignalingResult<()>
+where
+    U: UserRepository + ServerGroupRepository + ChannelRepository + ChatMessageRepository + 'static,
+    P: PermissionRepository + 'static,
+    B: BanRepository + 'static,
+{
+    let kanal = ChannelRepository::get_by_id(state.db.as_ref(), channel_id.inner())
+        .await
+        .map_err(|e| SignalingError::intern(e.to_string()))?
+        .ok_or_else(|| SignalingError::NichtGefunden(format!("Kanal {channel_id}")))?;
+    if state.presence.channel_von_client(&user_id) == Some(channel_id)
+        || (kanal.password_hash.is_none() && kanal.max_clients <= 0 && !kanal.join_by_approval)
+    {
+        return Ok(());
+    }
+    let zutritt = state.einladungen.hat_zutritt(user_id, channel_id);
+
+    let ausnahmen = match state
+        .permission_service
+        .alle_berechtigungen_holen(user_id.inner(), channel_id.inner())
+        .await
+    {
+        Ok(perms) => perms,
+        Err(e) => {
+            tracing::warn!(user_id = %user_id, fehler = %e, "Beitritts-Ausnahmen nicht pruefbar");
+            Default::default()
+        }
+    };
+    let gewaehrt = |key: &str| {
+        matches!(
+            ausnahmen.get(key),
+            Some(BerechtigungsWert::TriState(TriState::Grant))
+        )
+    };
+
+    if kanal.join_by_approval && !zutritt && !gewaehrt(BEITRITT_FREIGEBEN) {
+        return Err(SignalingError::ZugriffVerweigert(
+            "Kanal nur mit Freigabe, bitte anklopfen".into(),
+        ));
+    }
+
+    if let Some(hash) = &kanal.password_hash {
+        if !zutritt && !gewaehrt(PASSWORT_AUSNAHME) {
+            let korrekt = passwort.is_some_and(|p| {
+                speakeasy_auth::passwort_verifizieren(p, hash).unwrap_or_else(|e| {
+                    tracing::warn!(channel_id = %channel_id, fehler = %e, "Kanal-Passwort nicht pruefbar");
+                    false
+                })
+            });
+            if !korrekt {
+                return Err(SignalingError::KanalPasswort);
+            }
+        }
+    }
+
+    if kanal.max_clients > 0
+        && !gewaehrt(LIMIT_AUSNAHME)
+        && state.presence.user_ids_in_channel(&channel_id).len() >= kanal.max_clients as usize
+    {
+        return Err(SignalingError::KanalVoll);
+    }
+    Ok(())
+}
+
 /// Verarbeitet Channel-Beitritt
 pub async fn handle_channel_join<U, P, B>(
     request: ChannelJoinRequest,
@@ -151,15 +347,22 @@ where
         Ok(true) => {}
     }
 
+    if let Err(e) = beitritt_pruefen(state, user_id, channel_id, request.password.as_deref()).await
+    {
+        tracing::debug!(user_id = %user_id, channel_id = %channel_id, fehler = %e, "Beitritt abgelehnt");
+        return ControlMessage::fehler(request_id, e);
+    }
+    state.einladungen.zutritt_einloesen(user_id, channel_id);
+
     // Aus altem Channel austreten wenn vorhanden
     let alter_channel = state.presence.channel_von_client(&user_id);
     if let Some(alter) = alter_channel {
         state.presence.channel_verlassen(&user_id);
         state.broadcaster.channel_verlassen(&user_id);
         state.channel_router.kanal_verlassen(&user_id);
+        state.langsammodus.verlassen(&user_id);
+        ssrc_melden(state, user_id, alter, None);
 
-        // Anderen Channel-Mitgliedern mitteilen dass User gegangen ist
-        // (wird vom Dispatcher als Event versendet)
         tracing::debug!(
             user_id = %user_id,
             alter_channel = %alter,
@@ -170,28 +373,42 @@ where
     // Neuen Channel beitreten
     state.presence.channel_beitreten(user_id, channel_id);
     state.broadcaster.channel_beitreten(user_id, channel_id);
+    let listen_only =
+        sendemodus_anwenden(state, user_id, channel_id, Some(request.listen_only)).await;
+    crate::notfall::beim_beitritt(state, user_id, channel_id).await;
+    if let Some(ssrc) = state.voice_state.ssrc_von_user(&user_id) {
+        ssrc_melden(state, user_id, channel_id, Some(ssrc));
+    }
 
-    // Aktuelle Clients im Channel fuer die Antwort ermitteln
-    let clients_im_channel = state
-        .presence
-        .clients_in_channel(&channel_id)
-        .iter()
-        .filter(|p| p.user_id != user_id)
-        .map(client_info_aus_presence)
-        .collect();
+    // Mitglieder fuer die Antwort (in sehr vollen Kanaelen nur eine Auswahl)
+    let mut antwort = crate::mitglieder::beitritts_antwort(state, user_id, channel_id, listen_only);
+    antwort.slow_mode_secs = intervall_laden(state, channel_id).await;
 
     tracing::info!(
         user_id = %user_id,
         channel_id = %channel_id,
+        listen_only,
+        mitglieder = antwort.member_count,
         "Client Channel beigetreten"
     );
 
+    ControlMessage::new(request_id, ControlPayload::ChannelJoinResponse(antwort))
+}
+
+/// Verarbeitet eine seitenweise Abfrage der Kanalmitglieder
+pub fn handle_channel_members<U, P, B>(
+    request: ChannelMembersRequest,
+    request_id: u32,
+    state: &Arc<SignalingState<U, P, B>>,
+) -> ControlMessage
+where
+    U: UserRepository + ServerGroupRepository + ChannelRepository + ChatMessageRepository + 'static,
+    P: PermissionRepository + 'static,
+    B: BanRepository + 'static,
+{
     ControlMessage::new(
         request_id,
-        ControlPayload::ChannelJoinResponse(ChannelJoinResponse {
-            channel_id,
-            clients: clients_im_channel,
-        }),
+        ControlPayload::ChannelMembersResponse(crate::mitglieder::seite_laden(state, &request)),
     )
 }
 
@@ -214,11 +431,13 @@ where
             state.presence.channel_verlassen(&user_id);
             state.broadcaster.channel_verlassen(&user_id);
             state.channel_router.kanal_verlassen(&user_id);
+            state.langsammodus.verlassen(&user_id);
+            ssrc_melden(state, user_id, channel_id, None);
 
             tracing::info!(user_id = %user_id, channel_id = %channel_id, "Client Channel verlassen");
 
             // Bestaetigung (leere Antwort = Erfolg)
-            ControlMessage::new(request_id, ControlPayload::ChannelList)
+            ControlMessage::new(request_id, ControlPayload::ChannelList(Default::default()))
         }
         Some(anderer) => {
             tracing::warn!(
@@ -280,6 +499,12 @@ where
     // Parent-ID konvertieren
     let parent_uuid = request.parent_id.map(|cid| cid.inner());
 
+    // Leeres Passwort = kein Passwort
+    let passwort_hash = match kanal_passwort_hashen(request.password.as_deref()) {
+        Ok(hash) => hash,
+        Err(e) => return ControlMessage::fehler(request_id, e),
+    };
+
     // Channel persistent in der Datenbank anlegen
     let kanal = match ChannelRepository::create(
         state.db.as_ref(),
@@ -287,7 +512,7 @@ where
             name: &request.name,
             parent_id: parent_uuid,
             topic: request.description.as_deref(),
-            password_hash: None, // Passwort wird gehasht gespeichert - hier erstmal kein Hashing
+            password_hash: passwort_hash.as_deref(),
             max_clients: request.max_clients.unwrap_or(0) as i64,
             is_default: false,
             sort_order: request.sort_order.unwrap_or(0) as i64,
@@ -304,10 +529,10 @@ where
                 fehler = %e,
                 "Channel-Erstellung in DB fehlgeschlagen"
             );
-            return ControlMessage::error(
+            return ControlMessage::fehler_mit_kontext(
                 request_id,
-                ErrorCode::InternalError,
-                format!("Channel konnte nicht erstellt werden: {}", e),
+                "Channel konnte nicht erstellt werden",
+                e,
             );
         }
     };
@@ -330,7 +555,8 @@ where
 /// Verarbeitet Channel-Bearbeitung
 ///
 /// Erfordert `b_channel_modify`-Berechtigung.
-/// Aenderungen werden persistent in der Datenbank gespeichert.
+/// Aenderungen werden persistent in der Datenbank gespeichert und per
+/// `ChannelEdited` an alle Clients verteilt.
 pub async fn handle_channel_edit<U, P, B>(
     request: ChannelEditRequest,
     request_id: u32,
@@ -365,24 +591,50 @@ where
         Ok(true) => {}
     }
 
+    if request
+        .slow_mode_secs
+        .is_some_and(|s| s > MAX_LANGSAMMODUS_SEK)
+    {
+        return ControlMessage::fehler(
+            request_id,
+            SignalingError::protokoll(format!(
+                "Langsam-Modus hoechstens {MAX_LANGSAMMODUS_SEK} Sekunden"
+            )),
+        );
+    }
+
+    // Leeres Passwort entfernt den Schutz
+    let password_hash = match request.password.as_deref() {
+        Some(passwort) => match kanal_passwort_hashen(Some(passwort)) {
+            Ok(hash) => Some(hash),
+            Err(e) => return ControlMessage::fehler(request_id, e),
+        },
+        None => None,
+    };
+
     // Update in der Datenbank durchfuehren
     let update = KanalUpdate {
         name: request.name.clone(),
         parent_id: None,
-        topic: request.description.map(|d| Some(d)),
-        password_hash: None, // Passwort-Hashing hier nicht implementiert
+        topic: request.description.map(Some),
+        password_hash,
         max_clients: request.max_clients.map(|m| m as i64),
         is_default: None,
         sort_order: request.sort_order.map(|s| s as i64),
+        slow_mode_secs: request.slow_mode_secs.map(i64::from),
+        edit_window_secs: None,
+        join_by_approval: request.join_by_approval,
     };
 
     match ChannelRepository::update(state.db.as_ref(), request.channel_id.inner(), update).await {
-        Ok(_) => {
+        Ok(kanal) => {
             tracing::info!(
                 user_id = %user_id,
                 channel_id = %request.channel_id,
+                slow_mode_secs = kanal.slow_mode_secs,
                 "Channel in DB aktualisiert"
             );
+            kanal_aenderung_verteilen(state, &kanal);
         }
         Err(e) => {
             tracing::error!(
@@ -391,15 +643,61 @@ where
                 fehler = %e,
                 "Channel-Update in DB fehlgeschlagen"
             );
-            return ControlMessage::error(
+            return ControlMessage::fehler_mit_kontext(
                 request_id,
-                ErrorCode::InternalError,
-                format!("Channel konnte nicht aktualisiert werden: {}", e),
+                "Channel konnte nicht aktualisiert werden",
+                e,
             );
         }
     }
 
-    ControlMessage::new(request_id, ControlPayload::ChannelList)
+    ControlMessage::new(request_id, ControlPayload::ChannelList(Default::default()))
+}
+
+/// Meldet einen geaenderten Kanal an alle Clients
+///
+/// Fuer Aenderungen ausserhalb von `ChannelEdit` (Commander): laedt den Kanal
+/// neu, uebernimmt seinen Langsam-Modus und verteilt `ChannelEdited`.
+pub async fn kanal_geaendert_melden<U, P, B>(
+    state: &Arc<SignalingState<U, P, B>>,
+    channel_id: ChannelId,
+) -> SignalingResult<()>
+where
+    U: UserRepository + ServerGroupRepository + ChannelRepository + ChatMessageRepository + 'static,
+    P: PermissionRepository + 'static,
+    B: BanRepository + 'static,
+{
+    let kanal = ChannelRepository::get_by_id(state.db.as_ref(), channel_id.inner())
+        .await
+        .map_err(|e| SignalingError::intern(e.to_string()))?
+        .ok_or_else(|| SignalingError::NichtGefunden(format!("Kanal {channel_id}")))?;
+    kanal_aenderung_verteilen(state, &kanal);
+    Ok(())
+}
+
+/// Aktualisiert den Langsam-Modus und sendet `ChannelEdited` an alle
+fn kanal_aenderung_verteilen<U, P, B>(
+    state: &Arc<SignalingState<U, P, B>>,
+    kanal: &speakeasy_db::models::KanalRecord,
+) where
+    U: UserRepository + ServerGroupRepository + ChannelRepository + ChatMessageRepository + 'static,
+    P: PermissionRepository + 'static,
+    B: BanRepository + 'static,
+{
+    let channel_id = ChannelId(kanal.id);
+    let anzahl = state.presence.user_ids_in_channel(&channel_id).len() as u32;
+    let channel = channel_info_aus_record(
+        kanal,
+        anzahl,
+        state.einstellungen.aktuell().chat_bearbeitungsfrist_sek,
+    );
+    state
+        .langsammodus
+        .intervall_setzen(channel_id, channel.slow_mode_secs);
+    state.broadcaster.an_alle_senden(ControlMessage::new(
+        0,
+        ControlPayload::ChannelEdited(ChannelEditedEvent { channel }),
+    ));
 }
 
 /// Verarbeitet Channel-Loeschung
@@ -444,15 +742,21 @@ where
     let betroffene_clients = state.presence.user_ids_in_channel(&request.channel_id);
     let ziel_channel = request.move_clients_to;
 
+    state.langsammodus.kanal_entfernen(&request.channel_id);
     for uid in betroffene_clients {
         state.presence.channel_verlassen(&uid);
         state.broadcaster.channel_verlassen(&uid);
         state.channel_router.kanal_verlassen(&uid);
+        state.langsammodus.verlassen(&uid);
 
         // Falls Ziel-Channel angegeben: dorthin verschieben
         if let Some(ziel) = ziel_channel {
             state.presence.channel_beitreten(uid, ziel);
             state.broadcaster.channel_beitreten(uid, ziel);
+            let ssrc = state.voice_state.ssrc_von_user(&uid);
+            if ssrc.is_some() {
+                ssrc_melden(state, uid, ziel, ssrc);
+            }
         }
     }
 
@@ -477,13 +781,966 @@ where
                 fehler = %e,
                 "Channel-Loeschung in DB fehlgeschlagen"
             );
-            return ControlMessage::error(
+            return ControlMessage::fehler_mit_kontext(
                 request_id,
-                ErrorCode::InternalError,
-                format!("Channel konnte nicht geloescht werden: {}", e),
+                "Channel konnte nicht geloescht werden",
+                e,
             );
         }
     }
+    state
+        .channel_router
+        .notfall()
+        .deaktivieren(&request.channel_id);
+
+    ControlMessage::new(request_id, ControlPayload::ChannelList(Default::default()))
+}
+
+/// Verarbeitet die Notfall-Stummschaltung eines Kanals
+///
+/// Erfordert ausdruecklich gewaehrtes `b_channel_emergency_mute`. Die
+/// Antwort ist das an den Kanal verteilte Event. Siehe
+/// [`crate::notfall::kanal_notfall_stumm`].
+pub async fn handle_channel_emergency_mute<U, P, B>(
+    request: ChannelEmergencyMuteRequest,
+    request_id: u32,
+    user_id: UserId,
+    state: &Arc<SignalingState<U, P, B>>,
+) -> ControlMessage
+where
+    U: UserRepository
+        + ServerGroupRepository
+        + ChannelRepository
+        + ChatMessageRepository
+        + AuditLogRepository
+        + 'static,
+    P: PermissionRepository + 'static,
+    B: BanRepository + 'static,
+{
+    match crate::notfall::kanal_notfall_stumm(state, user_id, request.channel_id, request.active)
+        .await
+    {
+        Ok(event) => {
+            ControlMessage::new(request_id, ControlPayload::ChannelEmergencyMuteEvent(event))
+        }
+        Err(e) => ControlMessage::fehler(request_id, e),
+    }
+}
 
-    ControlMessage::new(request_id, ControlPayload::ChannelList)
+/// Behandelt eine SoundboardPlay-Anfrage
+pub async fn handle_soundboard_play<U, P, B>(
+    request: SoundboardPlayRequest,
+    request_id: u32,
+    user_id: UserId,
+    state: &Arc<SignalingState<U, P, B>>,
+) -> ControlMessage
+where
+    U: UserRepository + ServerGroupRepository + ChannelRepository + ChatMessageRepository + 'static,
+    P: PermissionRepository + 'static,
+    B: BanRepository + 'static,
+{
+    let Some(quelle) = state.sound_quelle() else {
+        return ControlMessage::fehler(
+            request_id,
+            speakeasy_core::SpeakeasyError::Konfiguration(
+                "Soundboard auf diesem Server nicht verfuegbar".into(),
+            ),
+        );
+    };
+    match crate::soundboard::abspielen(
+        state,
+        quelle.as_ref(),
+        user_id,
+        request.channel_id,
+        &request.sound_id,
+    )
+    .await
+    {
+        Ok(event) => ControlMessage::new(request_id, ControlPayload::SoundboardPlayback(event)),
+        Err(e) => ControlMessage::fehler(request_id, e),
+    }
+}
+
+/// Behandelt eine SoundboardStop-Anfrage (Bestaetigung = Echo der Anfrage)
+pub async fn handle_soundboard_stop<U, P, B>(
+    request: SoundboardStopRequest,
+    request_id: u32,
+    user_id: UserId,
+    state: &Arc<SignalingState<U, P, B>>,
+) -> ControlMessage
+where
+    U: UserRepository + ServerGroupRepository + ChannelRepository + ChatMessageRepository + 'static,
+    P: PermissionRepository + 'static,
+    B: BanRepository + 'static,
+{
+    match crate::soundboard::anhalten(state, user_id, request.channel_id, request.playback_id).await
+    {
+        Ok(_) => ControlMessage::new(request_id, ControlPayload::SoundboardStop(request)),
+        Err(e) => ControlMessage::fehler(request_id, e),
+    }
+}
+
+/// Behandelt eine Kanal-Einladung (siehe [`crate::einladung::einladen`])
+pub async fn handle_channel_invite<U, P, B>(
+    request: ChannelInviteRequest,
+    request_id: u32,
+    user_id: UserId,
+    state: &Arc<SignalingState<U, P, B>>,
+) -> ControlMessage
+where
+    U: UserRepository + ServerGroupRepository + ChannelRepository + ChatMessageRepository + 'static,
+    P: PermissionRepository + 'static,
+    B: BanRepository + 'static,
+{
+    match crate::einladung::einladen(state, user_id, request).await {
+        Ok(antwort) => {
+            ControlMessage::new(request_id, ControlPayload::ChannelInviteResponse(antwort))
+        }
+        Err(e) => ControlMessage::fehler(request_id, e),
+    }
+}
+
+/// Beantwortet eine Kanal-Einladung
+///
+/// Bei Annahme tritt der Eingeladene ohne Passwort und Freigabe bei; die
+/// Antwort ist dann die `ChannelJoinResponse`. Bei Ablehnung ist sie das
+/// Ergebnis-Event, das auch der Einladende erhaelt.
+pub async fn handle_channel_invite_answer<U, P, B>(
+    request: ChannelInviteAnswerRequest,
+    request_id: u32,
+    user_id: UserId,
+    state: &Arc<SignalingState<U, P, B>>,
+) -> ControlMessage
+where
+    U: UserRepository + ServerGroupRepository + ChannelRepository + ChatMessageRepository + 'static,
+    P: PermissionRepository + 'static,
+    B: BanRepository + 'static,
+{
+    let einladung = match state
+        .einladungen
+        .einladung_einloesen(&request.invite_id, user_id)
+    {
+        Eingeloest::Gueltig(einladung) => einladung,
+        Eingeloest::Abgelaufen(einladung) => {
+            crate::einladung::einladung_ausgang_melden(
+                state,
+                &request.invite_id,
+                &einladung,
+                ChannelInviteOutcome::Expired,
+            );
+            return ControlMessage::fehler(
+                request_id,
+                SignalingError::NichtGefunden(format!(
+                    "Einladung {} ist abgelaufen",
+                    request.invite_id
+                )),
+            );
+        }
+        Eingeloest::Unbekannt => {
+            return ControlMessage::fehler(
+                request_id,
+                SignalingError::NichtGefunden(format!("Einladung {}", request.invite_id)),
+            );
+        }
+    };
+
+    if !request.accept {
+        let event = crate::einladung::einladung_ausgang_melden(
+            state,
+            &request.invite_id,
+            &einladung,
+            ChannelInviteOutcome::Declined,
+        );
+        return ControlMessage::new(request_id, ControlPayload::ChannelInviteResult(event));
+    }
+
+    state
+        .einladungen
+        .zutritt_gewaehren(user_id, einladung.channel_id);
+    let antwort = handle_channel_join(
+        ChannelJoinRequest {
+            channel_id: einladung.channel_id,
+            password: None,
+            listen_only: request.listen_only,
+        },
+        request_id,
+        user_id,
+        state,
+    )
+    .await;
+    if matches!(antwort.payload, ControlPayload::ChannelJoinResponse(_)) {
+        crate::einladung::einladung_ausgang_melden(
+            state,
+            &request.invite_id,
+            &einladung,
+            ChannelInviteOutcome::Accepted,
+        );
+    } else {
+        // Beitritt gescheitert (z.B. Kanal voll): Zutritt nicht stehen lassen
+        state
+            .einladungen
+            .zutritt_einloesen(user_id, einladung.channel_id);
+    }
+    antwort
+}
+
+/// Behandelt eine Beitrittsanfrage (siehe [`crate::einladung::anklopfen`])
+pub async fn handle_channel_knock<U, P, B>(
+    request: ChannelKnockRequest,
+    request_id: u32,
+    user_id: UserId,
+    state: &Arc<SignalingState<U, P, B>>,
+) -> ControlMessage
+where
+    U: UserRepository + ServerGroupRepository + ChannelRepository + ChatMessageRepository + 'static,
+    P: PermissionRepository + 'static,
+    B: BanRepository + 'static,
+{
+    match crate::einladung::anklopfen(state, user_id, request).await {
+        Ok(antwort) => {
+            ControlMessage::new(request_id, ControlPayload::ChannelKnockResponse(antwort))
+        }
+        Err(e) => ControlMessage::fehler(request_id, e),
+    }
+}
+
+/// Laesst einen Anfragenden ein oder lehnt ihn ab
+///
+/// Die Antwort ist das Ergebnis-Event; siehe
+/// [`crate::einladung::anfrage_beantworten`].
+pub async fn handle_channel_knock_answer<U, P, B>(
+    request: ChannelKnockAnswerRequest,
+    request_id: u32,
+    user_id: UserId,
+    state: &Arc<SignalingState<U, P, B>>,
+) -> ControlMessage
+where
+    U: UserRepository + ServerGroupRepository + ChannelRepository + ChatMessageRepository + 'static,
+    P: PermissionRepository + 'static,
+    B: BanRepository + 'static,
+{
+    match crate::einladung::anfrage_beantworten(state, user_id, request).await {
+        Ok(event) => ControlMessage::new(request_id, ControlPayload::ChannelKnockResult(event)),
+        Err(e) => ControlMessage::fehler(request_id, e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::handlers::client_handler::handle_client_update;
+    use crate::mitglieder::STANDARD_VORSCHAU;
+    use crate::server_state::SignalingConfig;
+    use crate::test_hilfen::{neuer_client, TestAufbau, TestState};
+    use speakeasy_db::models::{BerechtigungsWert, BerechtigungsZiel, TriState};
+    use speakeasy_db::PermissionRepository;
+    use speakeasy_protocol::control::{
+        ChannelInviteEvent, ChannelJoinResponse, ChannelKnockEvent, ChannelKnockResultEvent,
+        ChannelMembersResponse, ClientUpdateRequest,
+    };
+
+    use std::time::Duration;
+
+    /// Root-Kategorien, Unterkategorien je Root, Kanaele je Unterkategorie
+    const BAUM: (usize, usize, usize) = (10, 10, 99);
+
+    async fn state(kanalbaum_teilweise_ab: usize) -> TestState {
+        TestAufbau::default()
+            .config(SignalingConfig {
+                kanalbaum_teilweise_ab,
+                ..Default::default()
+            })
+            .bauen()
+            .await
+    }
+
+    async fn kanal_anlegen(state: &TestState, name: &str, parent: Option<ChannelId>) -> ChannelId {
+        let record = ChannelRepository::create(
+            state.db.as_ref(),
+            NeuerKanal {
+                name,
+                parent_id: parent.map(|p| p.inner()),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        ChannelId(record.id)
+    }
+
+    /// Legt den Testbaum an und gibt (Roots, erste Unterkategorie, erster Kanal) zurueck
+    async fn grossen_baum_anlegen(state: &TestState) -> (Vec<ChannelId>, ChannelId, ChannelId) {
+        let (roots, mitte, blaetter) = BAUM;
+        let mut root_ids = Vec::new();
+        let mut erste = None;
+        for r in 0..roots {
+            let root = kanal_anlegen(state, &format!("Kategorie {r}"), None).await;
+            root_ids.push(root);
+            for m in 0..mitte {
+                let kategorie = kanal_anlegen(state, &format!("Bereich {r}.{m}"), Some(root)).await;
+                for b in 0..blaetter {
+                    let kanal =
+                        kanal_anlegen(state, &format!("Raum {r}.{m}.{b}"), Some(kategorie)).await;
+                    erste.get_or_insert((kategorie, kanal));
+                }
+            }
+        }
+        let (kategorie, kanal) = erste.unwrap();
+        (root_ids, kategorie, kanal)
+    }
+
+    fn liste(antwort: ControlMessage) -> ChannelListResponse {
+        match antwort.payload {
+            ControlPayload::ChannelListResponse(liste) => liste,
+            andere => panic!("Erwartet ChannelListResponse, erhalten: {andere:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn grosser_baum_wird_teilweise_geladen() {
+        let state = state(500).await;
+        let (roots, kategorie, kanal) = grossen_baum_anlegen(&state).await;
+        let user_id = neuer_client(&state, Some(kanal));
+
+        // Snapshot: Root-Ebene plus Pfad zum eigenen Kanal
+        let antwort = handle_channel_list(ChannelListRequest::default(), 1, user_id, &state).await;
+        let json = antwort.to_json().unwrap();
+        assert!(
+            json.len() < 16 * 1024,
+            "Snapshot zu gross: {} Bytes",
+            json.len()
+        );
+
+        let snapshot = liste(antwort);
+        assert!(snapshot.partial);
+        assert_eq!(snapshot.channels.len(), roots.len() + 2);
+        assert!(snapshot
+            .channels
+            .iter()
+            .filter(|k| k.parent_id.is_none())
+            .all(|k| k.has_children && k.child_count == BAUM.1 as u32));
+        let eigener = snapshot
+            .channels
+            .iter()
+            .find(|k| k.channel_id == kanal)
+            .unwrap();
+        assert_eq!(eigener.current_clients, 1);
+        assert_eq!(eigener.parent_id, Some(kategorie));
+        assert!(!eigener.has_children);
+
+        // Aufklappen einer Root-Kategorie liefert genau ihre Kinder
+        let antwort = handle_channel_tree_expand(
+            ChannelTreeExpandRequest {
+                channel_id: roots[3],
+                depth: None,
+            },
+            2,
+            &state,
+        )
+        .await;
+        let teilbaum = liste(antwort);
+        assert!(teilbaum.partial);
+        assert_eq!(teilbaum.channels.len(), 1 + BAUM.1);
+        assert_eq!(teilbaum.channels[0].channel_id, roots[3]);
+        assert!(!teilbaum.channels[0].has_children);
+        assert!(teilbaum.channels[1..].iter().all(|k| {
+            k.parent_id == Some(roots[3]) && k.has_children && k.child_count == BAUM.2 as u32
+        }));
+
+        // Angefragte Tiefe wird oberhalb der Schwelle begrenzt
+        let antwort = handle_channel_tree_expand(
+            ChannelTreeExpandRequest {
+                channel_id: roots[0],
+                depth: Some(50),
+            },
+            3,
+            &state,
+        )
+        .await;
+        let teilbaum = liste(antwort);
+        assert_eq!(teilbaum.channels.len(), 1 + BAUM.1 + BAUM.1 * BAUM.2);
+        assert!(teilbaum.channels.iter().all(|k| !k.has_children));
+
+        // Kanalliste ab einem Kanal
+        let antwort = handle_channel_list(
+            ChannelListRequest {
+                parent_id: Some(kategorie),
+                depth: None,
+            },
+            4,
+            user_id,
+            &state,
+        )
+        .await;
+        assert_eq!(liste(antwort).channels.len(), 1 + BAUM.2);
+    }
+
+    #[tokio::test]
+    async fn kleiner_baum_wird_vollstaendig_geliefert() {
+        let state = state(500).await;
+        let root = kanal_anlegen(&state, "Lobby", None).await;
+        let unter = kanal_anlegen(&state, "Unterkanal", Some(root)).await;
+        let user_id = neuer_client(&state, Some(unter));
+
+        let antwort = handle_channel_list(ChannelListRequest::default(), 1, user_id, &state).await;
+        let liste = liste(antwort);
+        assert!(!liste.partial);
+        let ids: Vec<ChannelId> = liste.channels.iter().map(|k| k.channel_id).collect();
+        assert_eq!(ids, vec![root, unter]);
+        assert_eq!(liste.channels[0].child_count, 1);
+        assert!(!liste.channels[0].has_children);
+    }
+
+    fn beitreten(antwort: ControlMessage) -> ChannelJoinResponse {
+        match antwort.payload {
+            ControlPayload::ChannelJoinResponse(antwort) => antwort,
+            andere => panic!("Erwartet ChannelJoinResponse, erhalten: {andere:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn grosser_kanal_kuerzt_beitritt_und_blaettert_vollstaendig() {
+        use std::collections::HashSet;
+
+        const MITGLIEDER: usize = 2000;
+        let state = state(500).await;
+        let halle = kanal_anlegen(&state, "Halle", None).await;
+        let mitglieder: Vec<UserId> = (0..MITGLIEDER)
+            .map(|_| neuer_client(&state, Some(halle)))
+            .collect();
+        state.aktivitaet.melden(mitglieder[42]);
+
+        let neu = neuer_client(&state, Some(kanal_anlegen(&state, "Vorraum", None).await));
+        let nachricht = handle_channel_join(join_anfrage(halle, false), 1, neu, &state).await;
+        let groesse = nachricht.to_json().unwrap().len();
+        let antwort = beitreten(nachricht);
+        assert!(antwort.members_partial);
+        assert_eq!(antwort.member_count as usize, MITGLIEDER + 1);
+        assert_eq!(antwort.clients.len(), STANDARD_VORSCHAU + 1);
+        assert_eq!(antwort.clients[0].user_id, neu);
+        assert_eq!(
+            antwort.clients[1].user_id, mitglieder[42],
+            "zuletzt aktiv zuerst"
+        );
+        assert!(
+            groesse < 32 * 1024,
+            "Beitrittsantwort zu gross: {groesse} Bytes"
+        );
+
+        let mut gesehen = HashSet::new();
+        let mut after = None;
+        loop {
+            let seite = match handle_channel_members(
+                ChannelMembersRequest {
+                    channel_id: halle,
+                    after,
+                    limit: Some(10_000),
+                },
+                2,
+                &state,
+            )
+            .payload
+            {
+                ControlPayload::ChannelMembersResponse(seite) => seite,
+                andere => panic!("Erwartet ChannelMembersResponse, erhalten: {andere:?}"),
+            };
+            let ChannelMembersResponse {
+                clients,
+                total,
+                next_after,
+                ..
+            } = seite;
+            assert_eq!(total as usize, MITGLIEDER + 1);
+            assert!(clients.len() <= crate::mitglieder::MAX_SEITE);
+            for client in clients {
+                assert!(gesehen.insert(client.user_id), "doppelt geliefert");
+            }
+            match next_after {
+                Some(cursor) => after = Some(cursor),
+                None => break,
+            }
+        }
+        assert_eq!(gesehen.len(), MITGLIEDER + 1);
+        assert!(mitglieder.iter().all(|id| gesehen.contains(id)));
+    }
+
+    fn join_anfrage(channel_id: ChannelId, listen_only: bool) -> ChannelJoinRequest {
+        ChannelJoinRequest {
+            channel_id,
+            password: None,
+            listen_only,
+        }
+    }
+
+    fn senden_anfordern() -> ClientUpdateRequest {
+        ClientUpdateRequest {
+            display_name: None,
+            is_input_muted: None,
+            is_output_muted: None,
+            transmit_requested: Some(true),
+        }
+    }
+
+    #[tokio::test]
+    async fn nur_zuhoeren_beim_beitritt() {
+        let state = state(500).await;
+        let lobby = kanal_anlegen(&state, "Lobby", None).await;
+        let buehne = kanal_anlegen(&state, "Buehne", None).await;
+        let sprecher = neuer_client(&state, Some(lobby));
+        let zuhoerer = neuer_client(&state, Some(lobby));
+        state
+            .voice_state
+            .client_registrieren(zuhoerer, 77, "127.0.0.1:40000".parse().unwrap());
+
+        let antwort =
+            beitreten(handle_channel_join(join_anfrage(buehne, false), 1, sprecher, &state).await);
+        assert!(!antwort.listen_only);
+        let antwort =
+            beitreten(handle_channel_join(join_anfrage(buehne, true), 2, zuhoerer, &state).await);
+        assert!(antwort.listen_only);
+        assert!(state.voice_state.sendeverbot_verbuchen(&zuhoerer));
+
+        // Andere Mitglieder sehen den Zuhoerer als solchen
+        let antwort =
+            beitreten(handle_channel_join(join_anfrage(buehne, false), 3, sprecher, &state).await);
+        let info = antwort
+            .clients
+            .iter()
+            .find(|c| c.user_id == zuhoerer)
+            .unwrap();
+        assert!(info.listen_only);
+        assert_eq!(info.ssrc, Some(77));
+
+        // Wechsel ohne Kanalaustritt: Senden anfordern
+        handle_client_update(senden_anfordern(), 4, zuhoerer, &state).await;
+        assert!(
+            !state
+                .presence
+                .client_presence(&zuhoerer)
+                .unwrap()
+                .nur_hoeren
+        );
+        assert!(!state.voice_state.sendeverbot_verbuchen(&zuhoerer));
+    }
+
+    #[tokio::test]
+    async fn verweigertes_senden_erzwingt_nur_zuhoeren() {
+        let state = state(500).await;
+        let lobby = kanal_anlegen(&state, "Lobby", None).await;
+        let vortrag = kanal_anlegen(&state, "Vortrag", None).await;
+        let user_id = neuer_client(&state, Some(lobby));
+        state
+            .db
+            .set_permission(
+                &BerechtigungsZiel::Benutzer(user_id.inner()),
+                "b_voice_transmit",
+                BerechtigungsWert::TriState(TriState::Deny),
+                Some(vortrag.inner()),
+            )
+            .await
+            .unwrap();
+
+        let antwort =
+            beitreten(handle_channel_join(join_anfrage(vortrag, false), 1, user_id, &state).await);
+        assert!(antwort.listen_only);
+
+        let antwort = handle_client_update(senden_anfordern(), 2, user_id, &state).await;
+        match antwort.payload {
+            ControlPayload::Error(e) => assert_eq!(e.code, ErrorCode::PermissionDenied),
+            andere => panic!("Erwartet Fehler, erhalten: {andere:?}"),
+        }
+
+        // Zurueck in der Lobby darf der Client wieder senden
+        let antwort =
+            beitreten(handle_channel_join(join_anfrage(lobby, false), 3, user_id, &state).await);
+        assert!(!antwort.listen_only);
+    }
+
+    #[tokio::test]
+    async fn unbekannter_kanal_beim_aufklappen() {
+        let state = state(500).await;
+        let antwort = handle_channel_tree_expand(
+            ChannelTreeExpandRequest {
+                channel_id: ChannelId::new(),
+                depth: None,
+            },
+            1,
+            &state,
+        )
+        .await;
+        match antwort.payload {
+            ControlPayload::Error(e) => assert_eq!(e.code, ErrorCode::NotFound),
+            andere => panic!("Erwartet Fehler, erhalten: {andere:?}"),
+        }
+    }
+
+    async fn kanal_mit_schutz(
+        state: &TestState,
+        passwort: Option<&str>,
+        max_clients: i64,
+    ) -> ChannelId {
+        let hash = passwort.map(|p| speakeasy_auth::passwort_hashen(p).unwrap());
+        let record = ChannelRepository::create(
+            state.db.as_ref(),
+            NeuerKanal {
+                name: "Geschuetzt",
+                password_hash: hash.as_deref(),
+                max_clients,
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        ChannelId(record.id)
+    }
+
+    fn abgelehnt(antwort: ControlMessage) -> ErrorCode {
+        match antwort.payload {
+            ControlPayload::Error(e) => e.code,
+            andere => panic!("Erwartet Fehler, erhalten: {andere:?}"),
+        }
+    }
+
+    async fn ausnahme_gewaehren(state: &TestState, user_id: UserId, kanal: ChannelId, key: &str) {
+        state
+            .db
+            .set_permission(
+                &BerechtigungsZiel::Benutzer(user_id.inner()),
+                key,
+                BerechtigungsWert::TriState(TriState::Grant),
+                Some(kanal.inner()),
+            )
+            .await
+            .unwrap();
+        state.permission_service.cache_komplett_invalidieren().await;
+    }
+
+    #[tokio::test]
+    async fn voller_kanal_lehnt_beitritt_ab() {
+        let state = state(500).await;
+        let lobby = kanal_anlegen(&state, "Lobby", None).await;
+        let klein = kanal_mit_schutz(&state, None, 1).await;
+        let erster = neuer_client(&state, Some(lobby));
+        let zweiter = neuer_client(&state, Some(lobby));
+
+        beitreten(handle_channel_join(join_anfrage(klein, false), 1, erster, &state).await);
+        let antwort = handle_channel_join(join_anfrage(klein, false), 2, zweiter, &state).await;
+        assert_eq!(abgelehnt(antwort), ErrorCode::ChannelFull);
+        assert_eq!(state.presence.channel_von_client(&zweiter), Some(lobby));
+
+        // Erneuter Beitritt des Mitglieds belegt keinen weiteren Platz
+        beitreten(handle_channel_join(join_anfrage(klein, false), 3, erster, &state).await);
+
+        ausnahme_gewaehren(&state, zweiter, klein, LIMIT_AUSNAHME).await;
+        beitreten(handle_channel_join(join_anfrage(klein, false), 4, zweiter, &state).await);
+        assert_eq!(state.presence.user_ids_in_channel(&klein).len(), 2);
+    }
+
+    #[tokio::test]
+    async fn kanal_passwort_wird_geprueft() {
+        let state = state(500).await;
+        let lobby = kanal_anlegen(&state, "Lobby", None).await;
+        let geheim = kanal_mit_schutz(&state, Some("sesam"), 0).await;
+        let user_id = neuer_client(&state, Some(lobby));
+
+        let antwort = handle_channel_join(join_anfrage(geheim, false), 1, user_id, &state).await;
+        assert_eq!(abgelehnt(antwort), ErrorCode::ChannelPasswordRequired);
+
+        let mut anfrage = join_anfrage(geheim, false);
+        anfrage.password = Some("falsch".into());
+        let antwort = handle_channel_join(anfrage.clone(), 2, user_id, &state).await;
+        assert_eq!(abgelehnt(antwort), ErrorCode::ChannelPasswordRequired);
+        assert_eq!(state.presence.channel_von_client(&user_id), Some(lobby));
+
+        anfrage.password = Some("sesam".into());
+        beitreten(handle_channel_join(anfrage, 3, user_id, &state).await);
+        assert_eq!(state.presence.channel_von_client(&user_id), Some(geheim));
+    }
+
+    #[tokio::test]
+    async fn ausnahme_umgeht_kanal_passwort() {
+        let state = state(500).await;
+        let lobby = kanal_anlegen(&state, "Lobby", None).await;
+        let geheim = kanal_mit_schutz(&state, Some("sesam"), 0).await;
+        let admin = neuer_client(&state, Some(lobby));
+
+        // Nur ausdruecklich gewaehrt, nicht schon durch fehlende Regel
+        let antwort = handle_channel_join(join_anfrage(geheim, false), 1, admin, &state).await;
+        assert_eq!(abgelehnt(antwort), ErrorCode::ChannelPasswordRequired);
+
+        ausnahme_gewaehren(&state, admin, geheim, PASSWORT_AUSNAHME).await;
+        beitreten(handle_channel_join(join_anfrage(geheim, false), 2, admin, &state).await);
+        assert_eq!(state.presence.channel_von_client(&admin), Some(geheim));
+    }
+
+    #[tokio::test]
+    async fn unbekannter_kanal_beim_beitritt() {
+        let state = state(500).await;
+        let lobby = kanal_anlegen(&state, "Lobby", None).await;
+        let user_id = neuer_client(&state, Some(lobby));
+        let antwort =
+            handle_channel_join(join_anfrage(ChannelId::new(), false), 1, user_id, &state).await;
+        assert_eq!(abgelehnt(antwort), ErrorCode::NotFound);
+    }
+
+    /// Zustellungen eines Empfaengers seit dem letzten Aufruf
+    fn zugestellt(rx: &mut tokio::sync::mpsc::Receiver<ControlMessage>) -> Vec<ControlPayload> {
+        let mut payloads = vec![];
+        while let Ok(nachricht) = rx.try_recv() {
+            payloads.push(nachricht.payload);
+        }
+        payloads
+    }
+
+    fn einladung_von(payloads: Vec<ControlPayload>) -> ChannelInviteEvent {
+        payloads
+            .into_iter()
+            .find_map(|p| match p {
+                ControlPayload::ChannelInviteReceived(ev) => Some(ev),
+                _ => None,
+            })
+            .expect("Einladung zugestellt")
+    }
+
+    fn einladen_anfrage(channel_id: ChannelId, ziel: UserId) -> ChannelInviteRequest {
+        ChannelInviteRequest {
+            channel_id,
+            target_user_id: ziel,
+            message: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn angenommene_einladung_umgeht_passwort() {
+        let state = state(500).await;
+        let lobby = kanal_anlegen(&state, "Lobby", None).await;
+        let geheim = kanal_mit_schutz(&state, Some("sesam"), 0).await;
+        let gastgeber = neuer_client(&state, Some(geheim));
+        let gast = neuer_client(&state, Some(lobby));
+        let mut rx_gast = state.broadcaster.client_registrieren(gast);
+        let mut rx_gastgeber = state.broadcaster.client_registrieren(gastgeber);
+
+        let antwort =
+            handle_channel_invite(einladen_anfrage(geheim, gast), 1, gastgeber, &state).await;
+        assert!(matches!(
+            antwort.payload,
+            ControlPayload::ChannelInviteResponse(_)
+        ));
+        let einladung = einladung_von(zugestellt(&mut rx_gast));
+        assert_eq!(einladung.inviter_id, gastgeber);
+        assert_eq!(einladung.channel_name, "Geschuetzt");
+
+        let antwort = handle_channel_invite_answer(
+            ChannelInviteAnswerRequest {
+                invite_id: einladung.invite_id.clone(),
+                accept: true,
+                listen_only: false,
+            },
+            2,
+            gast,
+            &state,
+        )
+        .await;
+        beitreten(antwort);
+        assert_eq!(state.presence.channel_von_client(&gast), Some(geheim));
+        assert!(zugestellt(&mut rx_gastgeber).iter().any(|p| matches!(
+            p,
+            ControlPayload::ChannelInviteResult(ev)
+                if ev.outcome == ChannelInviteOutcome::Accepted && ev.target_user_id == gast
+        )));
+
+        // Einmalig: zurueck in die Lobby, dann wieder mit Passwortpflicht
+        beitreten(handle_channel_join(join_anfrage(lobby, false), 3, gast, &state).await);
+        let antwort = handle_channel_join(join_anfrage(geheim, false), 4, gast, &state).await;
+        assert_eq!(abgelehnt(antwort), ErrorCode::ChannelPasswordRequired);
+    }
+
+    #[tokio::test]
+    async fn einladung_nur_an_verbundene_und_von_mitgliedern() {
+        let state = state(500).await;
+        let lobby = kanal_anlegen(&state, "Lobby", None).await;
+        let buehne = kanal_anlegen(&state, "Buehne", None).await;
+        let gastgeber = neuer_client(&state, Some(buehne));
+        let gast = neuer_client(&state, Some(lobby));
+
+        let antwort = handle_channel_invite(
+            einladen_anfrage(buehne, UserId::new()),
+            1,
+            gastgeber,
+            &state,
+        )
+        .await;
+        assert_eq!(abgelehnt(antwort), ErrorCode::NotFound);
+
+        // Nur Mitglieder laden in ihren Kanal ein
+        let antwort =
+            handle_channel_invite(einladen_anfrage(buehne, gastgeber), 2, gast, &state).await;
+        assert_eq!(abgelehnt(antwort), ErrorCode::PermissionDenied);
+    }
+
+    #[tokio::test]
+    async fn einladung_verfaellt_und_meldet_ablauf() {
+        let state = state(500).await;
+        let lobby = kanal_anlegen(&state, "Lobby", None).await;
+        let buehne = kanal_anlegen(&state, "Buehne", None).await;
+        let gastgeber = neuer_client(&state, Some(buehne));
+        let gast = neuer_client(&state, Some(lobby));
+        let mut rx_gast = state.broadcaster.client_registrieren(gast);
+        let mut rx_gastgeber = state.broadcaster.client_registrieren(gastgeber);
+
+        handle_channel_invite(einladen_anfrage(buehne, gast), 1, gastgeber, &state).await;
+        let einladung = einladung_von(zugestellt(&mut rx_gast));
+
+        // Erst nach den DB-Zugriffen anhalten, sonst laeuft der Pool-Timeout ab
+        tokio::time::pause();
+        tokio::time::sleep(state.einladungen.gueltigkeit() + Duration::from_secs(1)).await;
+        assert!(zugestellt(&mut rx_gastgeber).iter().any(|p| matches!(
+            p,
+            ControlPayload::ChannelInviteResult(ev) if ev.outcome == ChannelInviteOutcome::Expired
+        )));
+
+        let antwort = handle_channel_invite_answer(
+            ChannelInviteAnswerRequest {
+                invite_id: einladung.invite_id,
+                accept: true,
+                listen_only: false,
+            },
+            2,
+            gast,
+            &state,
+        )
+        .await;
+        assert_eq!(abgelehnt(antwort), ErrorCode::NotFound);
+        assert_eq!(state.presence.channel_von_client(&gast), Some(lobby));
+    }
+
+    async fn kanal_mit_freigabe(state: &TestState) -> ChannelId {
+        let kanal = kanal_anlegen(state, "Besprechung", None).await;
+        ChannelRepository::update(
+            state.db.as_ref(),
+            kanal.inner(),
+            KanalUpdate {
+                join_by_approval: Some(true),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        kanal
+    }
+
+    #[tokio::test]
+    async fn anklopfen_mit_freigabe_erlaubt_beitritt() {
+        let state = state(500).await;
+        let lobby = kanal_anlegen(&state, "Lobby", None).await;
+        let besprechung = kanal_mit_freigabe(&state).await;
+        let leitung = neuer_client(&state, Some(besprechung));
+        let gast = neuer_client(&state, Some(lobby));
+        let mut rx_leitung = state.broadcaster.client_registrieren(leitung);
+        let mut rx_gast = state.broadcaster.client_registrieren(gast);
+        let klopfen = || ChannelKnockRequest {
+            channel_id: besprechung,
+            message: Some("Darf ich?".into()),
+        };
+
+        // Ohne Zutritt kein direkter Beitritt
+        let antwort = handle_channel_join(join_anfrage(besprechung, false), 1, gast, &state).await;
+        assert_eq!(abgelehnt(antwort), ErrorCode::PermissionDenied);
+
+        // Niemand darf freigeben: Anfrage wird abgelehnt
+        let antwort = handle_channel_knock(klopfen(), 2, gast, &state).await;
+        assert_eq!(abgelehnt(antwort), ErrorCode::NotFound);
+
+        ausnahme_gewaehren(&state, leitung, besprechung, BEITRITT_FREIGEBEN).await;
+        let antwort = handle_channel_knock(klopfen(), 3, gast, &state).await;
+        assert!(matches!(
+            antwort.payload,
+            ControlPayload::ChannelKnockResponse(_)
+        ));
+        let anfrage: ChannelKnockEvent = zugestellt(&mut rx_leitung)
+            .into_iter()
+            .find_map(|p| match p {
+                ControlPayload::ChannelKnockReceived(ev) => Some(ev),
+                _ => None,
+            })
+            .expect("Anfrage zugestellt");
+        assert_eq!(anfrage.requester_id, gast);
+
+        // Nur Freigebende duerfen antworten
+        let antwort = handle_channel_knock_answer(
+            ChannelKnockAnswerRequest {
+                knock_id: anfrage.knock_id.clone(),
+                admit: true,
+            },
+            4,
+            gast,
+            &state,
+        )
+        .await;
+        assert_eq!(abgelehnt(antwort), ErrorCode::PermissionDenied);
+
+        let antwort = handle_channel_knock_answer(
+            ChannelKnockAnswerRequest {
+                knock_id: anfrage.knock_id,
+                admit: true,
+            },
+            5,
+            leitung,
+            &state,
+        )
+        .await;
+        let ergebnis = match antwort.payload {
+            ControlPayload::ChannelKnockResult(ev) => ev,
+            andere => panic!("Erwartet ChannelKnockResult, erhalten: {andere:?}"),
+        };
+        assert_eq!(ergebnis.outcome, ChannelInviteOutcome::Accepted);
+        assert_eq!(ergebnis.decided_by, Some(leitung));
+        assert!(zugestellt(&mut rx_gast).iter().any(|p| matches!(
+            p,
+            ControlPayload::ChannelKnockResult(ChannelKnockResultEvent { requester_id, .. })
+                if *requester_id == gast
+        )));
+
+        beitreten(handle_channel_join(join_anfrage(besprechung, false), 6, gast, &state).await);
+        assert_eq!(state.presence.channel_von_client(&gast), Some(besprechung));
+    }
+
+    #[tokio::test]
+    async fn abgelehnte_anfrage_gewaehrt_keinen_zutritt() {
+        let state = state(500).await;
+        let lobby = kanal_anlegen(&state, "Lobby", None).await;
+        let besprechung = kanal_mit_freigabe(&state).await;
+        let leitung = neuer_client(&state, Some(besprechung));
+        let gast = neuer_client(&state, Some(lobby));
+        ausnahme_gewaehren(&state, leitung, besprechung, BEITRITT_FREIGEBEN).await;
+
+        let knock_id = match handle_channel_knock(
+            ChannelKnockRequest {
+                channel_id: besprechung,
+                message: None,
+            },
+            1,
+            gast,
+            &state,
+        )
+        .await
+        .payload
+        {
+            ControlPayload::ChannelKnockResponse(r) => r.knock_id,
+            andere => panic!("Erwartet ChannelKnockResponse, erhalten: {andere:?}"),
+        };
+        handle_channel_knock_answer(
+            ChannelKnockAnswerRequest {
+                knock_id,
+                admit: false,
+            },
+            2,
+            leitung,
+            &state,
+        )
+        .await;
+
+        let antwort = handle_channel_join(join_anfrage(besprechung, false), 3, gast, &state).await;
+        assert_eq!(abgelehnt(antwort), ErrorCode::PermissionDenied);
+    }
 }
@@ -1,19 +1,28 @@
-//! Chat-Handler – Nachrichten senden, editieren, loeschen, History
+//! Chat-Handler – Nachrichten senden, editieren, loeschen, History, Suche
 //!
-//! Routet Chat-Nachrichten ueber den ChatService und sendet
-//! eingehende Nachrichten an alle Clients im Channel.
+//! Routet Chat-Nachrichten ueber den ChatService und meldet neue,
+//! bearbeitete und geloeschte Nachrichten an alle anderen Clients im
+//! Channel. Der Ausloeser selbst erhaelt nur die Antwort auf seine Anfrage,
+//! damit die Nachricht bei ihm nicht doppelt erscheint. Neue Nachrichten
+//! prueft vorher der Langsam-Modus des Kanals, Aenderungen die
+//! Bearbeitungsfrist.
 
-use speakeasy_core::types::UserId;
+use speakeasy_chat::{ChatNachricht, NachrichtenTyp};
+use speakeasy_core::types::{ChannelId, UserId};
 use speakeasy_db::{
     repository::UserRepository, BanRepository, ChannelRepository, ChatMessageRepository,
     PermissionRepository, ServerGroupRepository,
 };
+use speakeasy_protocol::chat_verlauf;
 use speakeasy_protocol::control::{
-    ChatDeleteRequest, ChatEditRequest, ChatHistoryRequest, ChatHistoryResponse, ChatMessageInfo,
-    ChatSendRequest, ChatSendResponse, ControlMessage, ControlPayload, ErrorCode,
+    ChatDeleteRequest, ChatDeletedEvent, ChatEditRequest, ChatEditedEvent, ChatHistoryChunk,
+    ChatHistoryComplete, ChatHistoryRequest, ChatHistoryResponse, ChatMessageEvent,
+    ChatMessageInfo, ChatSearchRequest, ChatSearchResponse, ChatSendRequest, ChatSendResponse,
+    ControlMessage, ControlPayload, ErrorCode,
 };
 use std::sync::Arc;
 
+use crate::bearbeitungsfrist::recht_ermitteln;
 use crate::server_state::SignalingState;
 
 /// Verarbeitet eine Chat-Nachricht
@@ -28,6 +37,16 @@ where
     P: PermissionRepository + 'static,
     B: BanRepository + 'static,
 {
+    if let Err(e) = crate::langsammodus::chat_pruefen(state, user_id, request.channel_id).await {
+        tracing::debug!(
+            user_id = %user_id,
+            channel_id = %request.channel_id,
+            fehler = %e,
+            "Chat-Nachricht durch Langsam-Modus abgelehnt"
+        );
+        return ControlMessage::fehler(request_id, e);
+    }
+
     let reply_to = request
         .reply_to
         .as_deref()
@@ -45,33 +64,37 @@ where
     {
         Ok(nachricht) => {
             let created_at = nachricht.created_at.timestamp() as u64;
+            let message_id = nachricht.id.to_string();
 
-            // Nachricht an alle Clients im Channel weiterleiten
-            let broadcast_msg = ControlMessage::new(
-                0,
-                ControlPayload::ChatSendResponse(ChatSendResponse {
-                    message_id: nachricht.id.to_string(),
-                    channel_id: request.channel_id,
-                    created_at,
-                }),
-            );
+            // Alle anderen Clients im Channel erhalten die ganze Nachricht
+            let sender_name = state
+                .presence
+                .client_presence(&user_id)
+                .map(|p| p.display_name)
+                .unwrap_or_default();
             state.broadcaster.an_channel_ausser_senden(
                 &request.channel_id,
                 &user_id,
-                broadcast_msg,
+                ControlMessage::new(
+                    0,
+                    ControlPayload::ChatMessage(ChatMessageEvent {
+                        message: nachricht_info(nachricht),
+                        sender_name,
+                    }),
+                ),
             );
 
             tracing::debug!(
                 user_id = %user_id,
                 channel_id = %request.channel_id,
-                message_id = %nachricht.id,
+                message_id = %message_id,
                 "Chat-Nachricht gesendet"
             );
 
             ControlMessage::new(
                 request_id,
                 ControlPayload::ChatSendResponse(ChatSendResponse {
-                    message_id: nachricht.id.to_string(),
+                    message_id,
                     channel_id: request.channel_id,
                     created_at,
                 }),
@@ -83,15 +106,47 @@ where
                 fehler = %e,
                 "Chat-Nachricht senden fehlgeschlagen"
             );
-            ControlMessage::error(
+            ControlMessage::fehler_mit_kontext(
                 request_id,
-                ErrorCode::InvalidRequest,
-                format!("Nachricht konnte nicht gesendet werden: {}", e),
+                "Nachricht konnte nicht gesendet werden",
+                e,
             )
         }
     }
 }
 
+/// Meldet die Nachricht zu einem abgeschlossenen Datei-Upload im Kanal
+///
+/// Der Upload laeuft per HTTP am Datei-Server vorbei an der
+/// Control-Verbindung, daher erhalten alle Clients im Kanal die Nachricht,
+/// auch der Hochladende. Gibt die Anzahl der Empfaenger zurueck.
+pub fn datei_nachricht_melden<U, P, B>(
+    state: &Arc<SignalingState<U, P, B>>,
+    nachricht: ChatNachricht,
+) -> usize
+where
+    U: UserRepository + ServerGroupRepository + ChannelRepository + ChatMessageRepository + 'static,
+    P: PermissionRepository + 'static,
+    B: BanRepository + 'static,
+{
+    let channel_id = ChannelId(nachricht.channel_id);
+    let sender_name = state
+        .presence
+        .client_presence(&UserId(nachricht.sender_id))
+        .map(|p| p.display_name)
+        .unwrap_or_default();
+    state.broadcaster.an_channel_senden(
+        &channel_id,
+        ControlMessage::new(
+            0,
+            ControlPayload::ChatMessage(ChatMessageEvent {
+                message: nachricht_info(nachricht),
+                sender_name,
+            }),
+        ),
+    )
+}
+
 /// Verarbeitet Chat-Nachricht editieren
 pub async fn handle_chat_edit<U, P, B>(
     request: ChatEditRequest,
@@ -115,12 +170,37 @@ where
         }
     };
 
+    let recht = match recht_ermitteln(state, user_id, message_id).await {
+        Ok(recht) => recht,
+        Err(e) => {
+            return ControlMessage::fehler_mit_kontext(
+                request_id,
+                "Nachricht konnte nicht editiert werden",
+                e,
+            );
+        }
+    };
+
     match state
         .chat_service
-        .nachricht_editieren(message_id, user_id.inner(), &request.content)
+        .nachricht_editieren(message_id, user_id.inner(), &request.content, recht)
         .await
     {
-        Ok(_) => {
+        Ok(nachricht) => {
+            let edited_at = nachricht.edited_at.unwrap_or_else(chrono::Utc::now);
+            state.broadcaster.an_channel_ausser_senden(
+                &ChannelId(nachricht.channel_id),
+                &user_id,
+                ControlMessage::new(
+                    0,
+                    ControlPayload::ChatEdited(ChatEditedEvent {
+                        message_id: request.message_id.clone(),
+                        channel_id: ChannelId(nachricht.channel_id),
+                        content: nachricht.content,
+                        edited_at: edited_at.to_rfc3339(),
+                    }),
+                ),
+            );
             tracing::debug!(
                 user_id = %user_id,
                 message_id = %message_id,
@@ -128,10 +208,10 @@ where
             );
             ControlMessage::new(request_id, ControlPayload::ChatEdit(request))
         }
-        Err(e) => ControlMessage::error(
+        Err(e) => ControlMessage::fehler_mit_kontext(
             request_id,
-            ErrorCode::PermissionDenied,
-            format!("Nachricht konnte nicht editiert werden: {}", e),
+            "Nachricht konnte nicht editiert werden",
+            e,
         ),
     }
 }
@@ -159,12 +239,34 @@ where
         }
     };
 
+    let recht = match recht_ermitteln(state, user_id, message_id).await {
+        Ok(recht) => recht,
+        Err(e) => {
+            return ControlMessage::fehler_mit_kontext(
+                request_id,
+                "Nachricht konnte nicht geloescht werden",
+                e,
+            );
+        }
+    };
+
     match state
         .chat_service
-        .nachricht_loeschen(message_id, user_id.inner())
+        .nachricht_loeschen(message_id, user_id.inner(), recht)
         .await
     {
-        Ok(()) => {
+        Ok(nachricht) => {
+            state.broadcaster.an_channel_ausser_senden(
+                &ChannelId(nachricht.channel_id),
+                &user_id,
+                ControlMessage::new(
+                    0,
+                    ControlPayload::ChatDeleted(ChatDeletedEvent {
+                        message_id: request.message_id.clone(),
+                        channel_id: ChannelId(nachricht.channel_id),
+                    }),
+                ),
+            );
             tracing::debug!(
                 user_id = %user_id,
                 message_id = %message_id,
@@ -172,10 +274,10 @@ where
             );
             ControlMessage::new(request_id, ControlPayload::ChatDelete(request))
         }
-        Err(e) => ControlMessage::error(
+        Err(e) => ControlMessage::fehler_mit_kontext(
             request_id,
-            ErrorCode::PermissionDenied,
-            format!("Nachricht konnte nicht geloescht werden: {}", e),
+            "Nachricht konnte nicht geloescht werden",
+            e,
         ),
     }
 }
@@ -191,59 +293,219 @@ where
     P: PermissionRepository + 'static,
     B: BanRepository + 'static,
 {
-    let before: Option<chrono::DateTime<chrono::Utc>> = request
-        .before
-        .as_deref()
-        .and_then(|s| chrono::DateTime::parse_from_rfc3339(s).ok())
-        .map(|dt| dt.with_timezone(&chrono::Utc));
+    match verlauf_laden(&request, state).await {
+        Ok(messages) => ControlMessage::new(
+            request_id,
+            ControlPayload::ChatHistoryResponse(ChatHistoryResponse {
+                channel_id: request.channel_id,
+                messages,
+            }),
+        ),
+        Err(e) => {
+            ControlMessage::fehler_mit_kontext(request_id, "History konnte nicht geladen werden", e)
+        }
+    }
+}
 
-    let limit = Some(request.limit.unwrap_or(50).min(100));
+/// Verarbeitet Chat-History-Anfrage mit `chunked`
+///
+/// Liefert die ganze Folge fuer dieselbe `request_id`: `ChatHistoryChunk`s
+/// (aelteste Nachricht zuerst) und zum Schluss `ChatHistoryComplete` mit dem
+/// Cursor fuer die naechste Seite. Scheitert das Laden, besteht die Folge
+/// nur aus einem `Error`.
+pub async fn handle_chat_history_chunked<U, P, B>(
+    request: ChatHistoryRequest,
+    request_id: u32,
+    state: &Arc<SignalingState<U, P, B>>,
+) -> Vec<ControlMessage>
+where
+    U: UserRepository + ServerGroupRepository + ChannelRepository + ChatMessageRepository + 'static,
+    P: PermissionRepository + 'static,
+    B: BanRepository + 'static,
+{
+    let messages = match verlauf_laden(&request, state).await {
+        Ok(messages) => messages,
+        Err(e) => {
+            return vec![ControlMessage::fehler_mit_kontext(
+                request_id,
+                "History konnte nicht geladen werden",
+                e,
+            )]
+        }
+    };
 
-    let anfrage = speakeasy_chat::HistoryAnfrage {
+    // Volle Seite: es kann Aelteres geben, weiter ab der aeltesten Nachricht
+    let total = messages.len() as u32;
+    let next_before = if i64::from(total) >= verlauf_limit(&request) {
+        messages.first().map(|n| n.created_at.clone())
+    } else {
+        None
+    };
+
+    let teile = chat_verlauf::aufteilen(messages);
+    let chunks = teile.len() as u32;
+    let mut folge: Vec<ControlMessage> = teile
+        .into_iter()
+        .enumerate()
+        .map(|(index, messages)| {
+            ControlMessage::new(
+                request_id,
+                ControlPayload::ChatHistoryChunk(ChatHistoryChunk {
+                    channel_id: request.channel_id,
+                    index: index as u32,
+                    messages,
+                }),
+            )
+        })
+        .collect();
+    folge.push(ControlMessage::new(
+        request_id,
+        ControlPayload::ChatHistoryComplete(ChatHistoryComplete {
+            channel_id: request.channel_id,
+            chunks,
+            total,
+            next_before,
+        }),
+    ));
+    folge
+}
+
+/// Verarbeitet eine Chat-Suche
+///
+/// Nur Mitglieder des Kanals duerfen suchen. Treffer kommen neueste zuerst;
+/// ist die Seite voll, zeigen `next_before` und `next_before_id` auf den
+/// aeltesten Treffer, damit zeitgleiche Treffer nicht verloren gehen.
+pub async fn handle_chat_search<U, P, B>(
+    request: ChatSearchRequest,
+    request_id: u32,
+    user_id: UserId,
+    state: &Arc<SignalingState<U, P, B>>,
+) -> ControlMessage
+where
+    U: UserRepository + ServerGroupRepository + ChannelRepository + ChatMessageRepository + 'static,
+    P: PermissionRepository + 'static,
+    B: BanRepository + 'static,
+{
+    if state.presence.channel_von_client(&user_id) != Some(request.channel_id) {
+        return ControlMessage::error(
+            request_id,
+            ErrorCode::PermissionDenied,
+            "Nicht Mitglied dieses Kanals",
+        );
+    }
+
+    let before = match request.before.as_deref() {
+        Some(s) => match chrono::DateTime::parse_from_rfc3339(s) {
+            Ok(dt) => Some(dt.with_timezone(&chrono::Utc)),
+            Err(_) => {
+                return ControlMessage::error(
+                    request_id,
+                    ErrorCode::InvalidRequest,
+                    "Ungueltiger Zeitstempel in 'before'",
+                )
+            }
+        },
+        None => None,
+    };
+    let before_id = match request.before_id.as_deref().map(uuid::Uuid::parse_str) {
+        Some(Ok(id)) => Some(id),
+        Some(Err(_)) => {
+            return ControlMessage::error(
+                request_id,
+                ErrorCode::InvalidRequest,
+                "Ungueltige Nachrichten-ID in 'before_id'",
+            )
+        }
+        None => None,
+    };
+
+    let anfrage = speakeasy_chat::SuchAnfrage {
         channel_id: request.channel_id.inner(),
+        query: request.query,
         before,
-        limit,
+        before_id,
+        limit: request.limit,
+        offset: 0,
     };
+    let seitengroesse = anfrage.seitengroesse();
 
-    match state.chat_service.history_laden(anfrage).await {
+    match state.chat_service.suchen(anfrage).await {
         Ok(nachrichten) => {
-            let messages = nachrichten
-                .into_iter()
-                .map(|n| ChatMessageInfo {
-                    message_id: n.id.to_string(),
-                    channel_id: request.channel_id,
-                    sender_id: UserId(n.sender_id),
-                    content: n.content,
-                    message_type: match n.message_type {
-                        speakeasy_chat::NachrichtenTyp::Text => "text".to_string(),
-                        speakeasy_chat::NachrichtenTyp::File => "file".to_string(),
-                        speakeasy_chat::NachrichtenTyp::System => "system".to_string(),
-                    },
-                    reply_to: n.reply_to.map(|id| id.to_string()),
-                    created_at: n.created_at.to_rfc3339(),
-                    edited_at: n.edited_at.map(|dt| dt.to_rfc3339()),
-                })
-                .collect();
-
+            let letzter = nachrichten
+                .last()
+                .filter(|_| nachrichten.len() as i64 >= seitengroesse);
+            let next_before = letzter.map(|n| n.created_at.to_rfc3339());
+            let next_before_id = letzter.map(|n| n.id.to_string());
             ControlMessage::new(
                 request_id,
-                ControlPayload::ChatHistoryResponse(ChatHistoryResponse {
+                ControlPayload::ChatSearchResponse(ChatSearchResponse {
                     channel_id: request.channel_id,
-                    messages,
+                    messages: nachrichten.into_iter().map(nachricht_info).collect(),
+                    next_before,
+                    next_before_id,
                 }),
             )
         }
-        Err(e) => {
+        Err(e) => ControlMessage::fehler_mit_kontext(request_id, "Suche fehlgeschlagen", e),
+    }
+}
+
+/// Angefragte Anzahl (Default 50, hoechstens 100)
+fn verlauf_limit(request: &ChatHistoryRequest) -> i64 {
+    request.limit.unwrap_or(50).min(100)
+}
+
+/// Laedt den angefragten Verlauf (aelteste zuerst)
+async fn verlauf_laden<U, P, B>(
+    request: &ChatHistoryRequest,
+    state: &Arc<SignalingState<U, P, B>>,
+) -> Result<Vec<ChatMessageInfo>, speakeasy_chat::ChatError>
+where
+    U: UserRepository + ServerGroupRepository + ChannelRepository + ChatMessageRepository + 'static,
+    P: PermissionRepository + 'static,
+    B: BanRepository + 'static,
+{
+    let before: Option<chrono::DateTime<chrono::Utc>> = request
+        .before
+        .as_deref()
+        .and_then(|s| chrono::DateTime::parse_from_rfc3339(s).ok())
+        .map(|dt| dt.with_timezone(&chrono::Utc));
+
+    let anfrage = speakeasy_chat::HistoryAnfrage {
+        channel_id: request.channel_id.inner(),
+        before,
+        limit: Some(verlauf_limit(request)),
+    };
+
+    let nachrichten = state
+        .chat_service
+        .history_laden(anfrage)
+        .await
+        .inspect_err(|e| {
             tracing::warn!(
                 channel_id = %request.channel_id,
                 fehler = %e,
                 "Chat-History laden fehlgeschlagen"
             );
-            ControlMessage::error(
-                request_id,
-                ErrorCode::InternalError,
-                format!("History konnte nicht geladen werden: {}", e),
-            )
-        }
+        })?;
+
+    Ok(nachrichten.into_iter().map(nachricht_info).collect())
+}
+
+/// Uebersetzt eine gespeicherte Nachricht ins Protokoll
+fn nachricht_info(n: ChatNachricht) -> ChatMessageInfo {
+    ChatMessageInfo {
+        message_id: n.id.to_string(),
+        channel_id: ChannelId(n.channel_id),
+        sender_id: UserId(n.sender_id),
+        content: n.content,
+        message_type: match n.message_type {
+            NachrichtenTyp::Text => "text".to_string(),
+            NachrichtenTyp::File => "file".to_string(),
+            NachrichtenTyp::System => "system".to_string(),
+        },
+        reply_to: n.reply_to.map(|id| id.to_string()),
+        created_at: n.created_at.to_rfc3339(),
+        edited_at: n.edited_at.map(|dt| dt.to_rfc3339()),
     }
 }
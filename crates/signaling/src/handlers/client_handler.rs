@@ -1,35 +1,42 @@
-//! Client-Handler – List, Kick, Ban, Move, Poke, Update
+//! Client-Handler – List, Kick, Ban, Move, Sammel-Move, Poke, Update, Namen
 //!
 //! Alle schreibenden Operationen erfordern Berechtigungspruefung.
 //! Permission-Keys folgen dem TeamSpeak-aehnlichen Schema (b_client_*).
 
 use speakeasy_core::types::{ChannelId, UserId};
 use speakeasy_db::{
-    repository::UserRepository, BanRepository, ChannelRepository, ChatMessageRepository,
+    models::{NeuerAuditEintrag, GELOESCHTER_BENUTZER},
+    repository::UserRepository,
+    AuditLogRepository, BanRepository, ChannelRepository, ChatMessageRepository,
     PermissionRepository, ServerGroupRepository,
 };
 use speakeasy_protocol::control::{
     ClientBanRequest, ClientInfo, ClientKickRequest, ClientListResponse, ClientMoveRequest,
-    ClientPokeRequest, ClientUpdateRequest, ControlMessage, ControlPayload, ErrorCode,
+    ClientMovedEvent, ClientPokeRequest, ClientUpdateRequest, ClientsMoveAllRequest,
+    ClientsMoveAllResponse, ClientsMovedEvent, ControlMessage, ControlPayload, ErrorCode,
+    MoveSkipReason, ResolveIdsRequest, ResolveIdsResponse, ResolvedChannel, ResolvedUser,
+    SkippedMove, StateDiffRequest, MAX_AUFLOESUNG_IDS, MAX_TRENNUNGSFRIST_SEK,
 };
+use speakeasy_voice::VoiceState;
+use std::collections::HashSet;
 use std::sync::Arc;
 use std::time::Duration;
 
+use crate::bereinigung::{self, Bereinigungsauftrag};
+use crate::error::{SignalingError, SignalingResult};
+use crate::handlers::voice_handler::{
+    sendemodus_anwenden, sendemodus_melden, sendemodus_neu_bewerten, senden_erlaubt, ssrc_melden,
+};
 use crate::presence::ClientPresence;
 use crate::server_state::SignalingState;
+use crate::trennung::{self, TrennungsZiel};
 
 /// Konvertiert ClientPresence in ClientInfo fuer Protokoll-Antworten
-fn client_info_aus_presence(presence: &ClientPresence) -> ClientInfo {
-    ClientInfo {
-        user_id: presence.user_id,
-        username: presence.username.clone(),
-        display_name: presence.display_name.clone(),
-        channel_id: presence.channel_id,
-        server_groups: vec![],
-        is_muted: presence.is_output_muted,
-        is_deafened: presence.is_output_muted,
-        is_input_muted: presence.is_input_muted,
-    }
+pub(crate) fn client_info_aus_presence(
+    presence: &ClientPresence,
+    voice_state: &VoiceState,
+) -> ClientInfo {
+    presence.client_info(voice_state.ssrc_von_user(&presence.user_id))
 }
 
 /// Verarbeitet Client-Listen-Anfrage
@@ -42,23 +49,55 @@ where
     P: PermissionRepository + 'static,
     B: BanRepository + 'static,
 {
+    // Version vor dem Abbild lesen, damit kein Ereignis verloren geht
+    let state_version = state.broadcaster.zustand_version();
     let clients: Vec<ClientInfo> = state
         .presence
         .alle_clients()
         .iter()
-        .map(client_info_aus_presence)
+        .map(|p| client_info_aus_presence(p, &state.voice_state))
         .collect();
 
     ControlMessage::new(
         request_id,
-        ControlPayload::ClientListResponse(ClientListResponse { clients }),
+        ControlPayload::ClientListResponse(ClientListResponse {
+            clients,
+            state_version,
+        }),
     )
 }
 
+/// Verarbeitet eine Diff-Anfrage (verpasste Presence-Ereignisse nachholen)
+///
+/// Liegt die Luecke nicht mehr im Replay-Ring, verlangt die Antwort ein
+/// neues Abbild per `ClientList`.
+pub async fn handle_state_diff<U, P, B>(
+    request: StateDiffRequest,
+    request_id: u32,
+    state: &Arc<SignalingState<U, P, B>>,
+) -> ControlMessage
+where
+    U: UserRepository + ServerGroupRepository + ChannelRepository + ChatMessageRepository + 'static,
+    P: PermissionRepository + 'static,
+    B: BanRepository + 'static,
+{
+    let antwort = state.broadcaster.diff_seit(request.since_version);
+    tracing::debug!(
+        seit = request.since_version,
+        aktuell = antwort.current_version,
+        ereignisse = antwort.events.len(),
+        abbild = antwort.snapshot_required,
+        "State-Diff"
+    );
+    ControlMessage::new(request_id, ControlPayload::StateDiffResponse(antwort))
+}
+
 /// Verarbeitet Client-Kick
 ///
 /// Erfordert `b_client_kick_server` (Server-Kick) oder
-/// `b_client_kick_channel` (Channel-Kick).
+/// `b_client_kick_channel` (Channel-Kick). Mit `grace_secs` wird der Kick
+/// nur angekuendigt (siehe [`crate::trennung`]); die Antwort ist dann die
+/// erste `PendingDisconnectNotice`.
 pub async fn handle_client_kick<U, P, B>(
     request: ClientKickRequest,
     request_id: u32,
@@ -66,7 +105,12 @@ pub async fn handle_client_kick<U, P, B>(
     state: &Arc<SignalingState<U, P, B>>,
 ) -> ControlMessage
 where
-    U: UserRepository + ServerGroupRepository + ChannelRepository + ChatMessageRepository + 'static,
+    U: UserRepository
+        + ServerGroupRepository
+        + ChannelRepository
+        + ChatMessageRepository
+        + AuditLogRepository
+        + 'static,
     P: PermissionRepository + 'static,
     B: BanRepository + 'static,
 {
@@ -104,56 +148,126 @@ where
         return ControlMessage::error(request_id, ErrorCode::NotFound, "Client nicht verbunden");
     }
 
-    let grund = request.reason.as_deref().unwrap_or("Gekickt");
+    if let Some(frist) = request.grace_secs.filter(|s| *s > 0) {
+        if frist > MAX_TRENNUNGSFRIST_SEK {
+            return ControlMessage::error(
+                request_id,
+                ErrorCode::InvalidRequest,
+                format!("Frist darf hoechstens {MAX_TRENNUNGSFRIST_SEK} Sekunden betragen"),
+            );
+        }
+        let meldung = trennung::ankuendigen(
+            state,
+            TrennungsZiel::Benutzer {
+                user_id: request.target_user_id,
+                nur_kanal: request.from_channel_only,
+            },
+            request.reason,
+            actor_id,
+            Duration::from_secs(frist.into()),
+        )
+        .await;
+        return ControlMessage::new(request_id, ControlPayload::PendingDisconnectNotice(meldung));
+    }
+
+    kick_ausfuehren(
+        state,
+        actor_id,
+        request.target_user_id,
+        request.from_channel_only,
+        request.reason.as_deref().unwrap_or("Gekickt"),
+    );
+
+    // Bestaetigung
+    ControlMessage::new(request_id, ControlPayload::ClientList)
+}
 
-    if request.from_channel_only {
+/// Kickt einen Client vom Server oder nur aus seinem Kanal
+///
+/// Gemeinsamer Weg fuer sofortige und angekuendigte Kicks; Berechtigungen
+/// prueft der Aufrufer.
+pub(crate) fn kick_ausfuehren<U, P, B>(
+    state: &Arc<SignalingState<U, P, B>>,
+    actor_id: UserId,
+    ziel: UserId,
+    nur_kanal: bool,
+    grund: &str,
+) where
+    U: UserRepository + ServerGroupRepository + ChannelRepository + ChatMessageRepository + 'static,
+    P: PermissionRepository + 'static,
+    B: BanRepository + 'static,
+{
+    if nur_kanal {
+        if let Some(channel_id) = state.presence.channel_von_client(&ziel) {
+            ssrc_melden(state, ziel, channel_id, None);
+        }
         // Nur aus Channel entfernen
-        state.presence.channel_verlassen(&request.target_user_id);
-        state.broadcaster.channel_verlassen(&request.target_user_id);
-        state
-            .channel_router
-            .kanal_verlassen(&request.target_user_id);
+        state.presence.channel_verlassen(&ziel);
+        state.broadcaster.channel_verlassen(&ziel);
+        state.channel_router.kanal_verlassen(&ziel);
+        state.langsammodus.verlassen(&ziel);
         tracing::info!(
             actor = %actor_id,
-            target = %request.target_user_id,
+            target = %ziel,
             grund = %grund,
             "Client aus Channel gekickt"
         );
     } else {
-        // Vom Server kicken – Verbindung wird vom Dispatcher getrennt
-        // Wir senden zuerst eine Benachrichtigung an den Client
-        let kick_msg = ControlMessage::error(
-            0,
-            ErrorCode::InvalidRequest,
-            format!("Du wurdest gekickt: {}", grund),
-        );
-        state
-            .broadcaster
-            .an_user_senden(&request.target_user_id, kick_msg);
-
-        // Cleanup im Presence-Manager (Verbindungstrennung folgt)
-        state.presence.client_getrennt(&request.target_user_id);
-        state.broadcaster.client_entfernen(&request.target_user_id);
-        state.voice_state.client_entfernen(&request.target_user_id);
-        state
-            .channel_router
-            .kanal_verlassen(&request.target_user_id);
+        // Vom Server kicken – zuerst die Benachrichtigung, dann trennen
+        client_trennen(state, ziel, kick_meldung(grund));
 
         tracing::info!(
             actor = %actor_id,
-            target = %request.target_user_id,
+            target = %ziel,
             grund = %grund,
             "Client vom Server gekickt"
         );
     }
+}
 
-    // Bestaetigung
-    ControlMessage::new(request_id, ControlPayload::ClientList)
+/// Benachrichtigung eines vom Server gekickten Clients
+pub(crate) fn kick_meldung(grund: &str) -> ControlMessage {
+    ControlMessage::error(
+        0,
+        ErrorCode::InvalidRequest,
+        format!("Du wurdest gekickt: {}", grund),
+    )
+}
+
+/// Benachrichtigt einen Client und trennt ihn vom Server
+///
+/// Gemeinsamer Weg fuer Kick, Ban und Kontoloeschung: raeumt Presence,
+/// Broadcaster, Voice-Zustand und Routing ab, sodass der UDP-Server die
+/// Pakete des Clients verwirft. Die Verbindung stellt die Benachrichtigung
+/// noch zu und schliesst dann (siehe [`crate::connection`]).
+pub fn client_trennen<U, P, B>(
+    state: &Arc<SignalingState<U, P, B>>,
+    user_id: UserId,
+    benachrichtigung: ControlMessage,
+) where
+    U: UserRepository + ServerGroupRepository + ChannelRepository + ChatMessageRepository + 'static,
+    P: PermissionRepository + 'static,
+    B: BanRepository + 'static,
+{
+    state.broadcaster.an_user_senden(&user_id, benachrichtigung);
+    if let Some(channel_id) = state.presence.channel_von_client(&user_id) {
+        ssrc_melden(state, user_id, channel_id, None);
+    }
+    state.presence.client_getrennt(&user_id);
+    state.broadcaster.client_entfernen(&user_id);
+    state.voice_state.client_entfernen(&user_id);
+    state.channel_router.kanal_verlassen(&user_id);
+    state.langsammodus.verlassen(&user_id);
+    state.aktivitaet.entfernen(&user_id);
+    state.trennungen.ziel_getrennt(&user_id);
 }
 
 /// Verarbeitet Client-Ban
 ///
-/// Erfordert `b_client_ban_server`-Berechtigung.
+/// Erfordert `b_client_ban_server`-Berechtigung. Mit `remove_content_secs`
+/// werden danach die Nachrichten und Dateien des Gebannten aus diesem
+/// Zeitraum entfernt (siehe [`bereinigung`]); scheitert das, bleibt der Ban
+/// bestehen und der Auftrag kann ueber den Commander fortgesetzt werden.
 pub async fn handle_client_ban<U, P, B>(
     request: ClientBanRequest,
     request_id: u32,
@@ -161,7 +275,12 @@ pub async fn handle_client_ban<U, P, B>(
     state: &Arc<SignalingState<U, P, B>>,
 ) -> ControlMessage
 where
-    U: UserRepository + ServerGroupRepository + ChannelRepository + ChatMessageRepository + 'static,
+    U: UserRepository
+        + ServerGroupRepository
+        + ChannelRepository
+        + ChatMessageRepository
+        + AuditLogRepository
+        + 'static,
     P: PermissionRepository + 'static,
     B: BanRepository + 'static,
 {
@@ -208,15 +327,7 @@ where
                 ErrorCode::Banned,
                 format!("Du wurdest gebannt: {}", grund),
             );
-            state
-                .broadcaster
-                .an_user_senden(&request.target_user_id, ban_msg);
-            state.presence.client_getrennt(&request.target_user_id);
-            state.broadcaster.client_entfernen(&request.target_user_id);
-            state.voice_state.client_entfernen(&request.target_user_id);
-            state
-                .channel_router
-                .kanal_verlassen(&request.target_user_id);
+            client_trennen(state, request.target_user_id, ban_msg);
 
             tracing::info!(
                 actor = %actor_id,
@@ -225,6 +336,22 @@ where
                 "Client gebannt"
             );
 
+            if let Some(sekunden) = request.remove_content_secs {
+                let auftrag = Bereinigungsauftrag {
+                    user_id: request.target_user_id,
+                    zeitraum: Duration::from_secs(sekunden),
+                    nur_kanal: None,
+                    aktor_id: Some(actor_id),
+                };
+                if let Err(e) = bereinigung::bereinigen(state, auftrag).await {
+                    tracing::error!(
+                        target = %request.target_user_id,
+                        fehler = %e,
+                        "Inhaltsbereinigung nach Ban fehlgeschlagen"
+                    );
+                }
+            }
+
             ControlMessage::new(request_id, ControlPayload::ClientList)
         }
         Err(e) => {
@@ -274,42 +401,336 @@ where
         return ControlMessage::error(request_id, ErrorCode::NotFound, "Client nicht verbunden");
     }
 
-    // Client in neuen Channel verschieben
-    state
-        .presence
-        .channel_beitreten(request.target_user_id, request.target_channel_id);
-    state
-        .broadcaster
-        .channel_beitreten(request.target_user_id, request.target_channel_id);
+    // Manuelles Verschieben gilt als Aktivitaet – sonst wuerde ein gerade
+    // aus dem AFK-Kanal zurueckgeholter Client sofort wieder verschoben
+    state.aktivitaet.melden(request.target_user_id);
+
+    tracing::info!(actor = %actor_id, "Client-Move angefordert");
+    client_verschieben(
+        state,
+        request.target_user_id,
+        request.target_channel_id,
+        request.reason,
+    );
+    sendemodus_neu_bewerten(state, request.target_user_id, request.target_channel_id).await;
+
+    ControlMessage::new(request_id, ControlPayload::ClientList)
+}
+
+/// Verschiebt einen Client in einen anderen Channel
+///
+/// Gemeinsamer Weg fuer `ClientMove` und die AFK-Erkennung: aktualisiert
+/// Presence und Broadcaster, sendet dem Client die neue Mitgliederliste
+/// und informiert alle Clients per `ClientMoved`.
+pub(crate) fn client_verschieben<U, P, B>(
+    state: &Arc<SignalingState<U, P, B>>,
+    user_id: UserId,
+    ziel: ChannelId,
+    grund: Option<String>,
+) where
+    U: UserRepository + ServerGroupRepository + ChannelRepository + ChatMessageRepository + 'static,
+    P: PermissionRepository + 'static,
+    B: BanRepository + 'static,
+{
+    let von = kanal_wechseln(state, user_id, ziel);
+    mitglieder_senden(state, user_id, ziel);
+
+    tracing::info!(
+        target = %user_id,
+        ziel_channel = %ziel,
+        grund = ?grund,
+        "Client verschoben"
+    );
+
+    state.broadcaster.zustand_senden(|state_version| {
+        ControlMessage::new(
+            0,
+            ControlPayload::ClientMoved(ClientMovedEvent {
+                user_id,
+                from_channel_id: von,
+                to_channel_id: ziel,
+                reason: grund,
+                state_version,
+            }),
+        )
+    });
+}
+
+/// Setzt Presence, Broadcaster und SSRC-Meldungen auf den Zielkanal um
+///
+/// Gibt den bisherigen Kanal zurueck.
+fn kanal_wechseln<U, P, B>(
+    state: &Arc<SignalingState<U, P, B>>,
+    user_id: UserId,
+    ziel: ChannelId,
+) -> Option<ChannelId>
+where
+    U: UserRepository + ServerGroupRepository + ChannelRepository + ChatMessageRepository + 'static,
+    P: PermissionRepository + 'static,
+    B: BanRepository + 'static,
+{
+    let von = state.presence.channel_von_client(&user_id);
+    state.presence.channel_beitreten(user_id, ziel);
+    state.broadcaster.channel_beitreten(user_id, ziel);
+    state.langsammodus.verlassen(&user_id);
+    if let Some(von) = von {
+        ssrc_melden(state, user_id, von, None);
+    }
+    if let Some(ssrc) = state.voice_state.ssrc_von_user(&user_id) {
+        ssrc_melden(state, user_id, ziel, Some(ssrc));
+    }
+    von
+}
 
-    // Den verschobenen Client informieren
+/// Sendet dem verschobenen Client die Mitgliederliste des Zielkanals
+fn mitglieder_senden<U, P, B>(
+    state: &Arc<SignalingState<U, P, B>>,
+    user_id: UserId,
+    ziel: ChannelId,
+) where
+    U: UserRepository + ServerGroupRepository + ChannelRepository + ChatMessageRepository + 'static,
+    P: PermissionRepository + 'static,
+    B: BanRepository + 'static,
+{
+    let listen_only = state
+        .presence
+        .client_presence(&user_id)
+        .is_some_and(|p| p.nur_hoeren);
     let move_msg = ControlMessage::new(
         0,
-        speakeasy_protocol::control::ControlPayload::ChannelJoinResponse(
-            speakeasy_protocol::control::ChannelJoinResponse {
-                channel_id: request.target_channel_id,
-                clients: state
-                    .presence
-                    .clients_in_channel(&request.target_channel_id)
-                    .iter()
-                    .filter(|p| p.user_id != request.target_user_id)
-                    .map(client_info_aus_presence)
-                    .collect(),
-            },
-        ),
+        ControlPayload::ChannelJoinResponse(crate::mitglieder::beitritts_antwort(
+            state,
+            user_id,
+            ziel,
+            listen_only,
+        )),
     );
-    state
-        .broadcaster
-        .an_user_senden(&request.target_user_id, move_msg);
+    state.broadcaster.an_user_senden(&user_id, move_msg);
+}
+
+/// Verarbeitet Sammel-Move (alle oder ausgewaehlte Clients eines Kanals)
+///
+/// Erfordert `b_client_move` im Quellkanal. Siehe [`clients_alle_verschieben`].
+pub async fn handle_clients_move_all<U, P, B>(
+    request: ClientsMoveAllRequest,
+    request_id: u32,
+    actor_id: UserId,
+    state: &Arc<SignalingState<U, P, B>>,
+) -> ControlMessage
+where
+    U: UserRepository
+        + ServerGroupRepository
+        + ChannelRepository
+        + ChatMessageRepository
+        + AuditLogRepository
+        + 'static,
+    P: PermissionRepository + 'static,
+    B: BanRepository + 'static,
+{
+    match clients_alle_verschieben(state, actor_id, request).await {
+        Ok(antwort) => {
+            ControlMessage::new(request_id, ControlPayload::ClientsMoveAllResponse(antwort))
+        }
+        Err(e) => ControlMessage::fehler(request_id, e),
+    }
+}
+
+/// Verschiebt alle (oder ausgewaehlte) Clients eines Kanals gemeinsam
+///
+/// Gemeinsamer Weg fuer den Control-Payload `ClientsMoveAll` und den
+/// Commander. Ablauf:
+///
+/// 1. Momentaufnahme der Mitglieder des Quellkanals
+/// 2. Pro Benutzer: Move-Power des Akteurs (`i_client_move_power`) muss
+///    mindestens `i_client_needed_move_power` des Ziels erreichen
+/// 3. Ohne weiteres `await`: wer den Quellkanal inzwischen verlassen hat,
+///    wird uebersprungen; danach wird die Kapazitaet des Zielkanals
+///    geprueft (`allow_partial` entscheidet zwischen Fehler und Teil-Move)
+///    und alle verbleibenden Clients werden verschoben
+///
+/// Statt einzelner `ClientMoved`-Ereignisse geht ein einziges
+/// `ClientsMoved` an alle Clients, dazu ein Audit-Eintrag mit der
+/// vollstaendigen Liste.
+pub async fn clients_alle_verschieben<U, P, B>(
+    state: &Arc<SignalingState<U, P, B>>,
+    actor_id: UserId,
+    request: ClientsMoveAllRequest,
+) -> SignalingResult<ClientsMoveAllResponse>
+where
+    U: UserRepository
+        + ServerGroupRepository
+        + ChannelRepository
+        + ChatMessageRepository
+        + AuditLogRepository
+        + 'static,
+    P: PermissionRepository + 'static,
+    B: BanRepository + 'static,
+{
+    let von = request.from_channel_id;
+    let nach = request.to_channel_id;
+    if von == nach {
+        return Err(SignalingError::protokoll(
+            "Quell- und Zielkanal sind identisch",
+        ));
+    }
+
+    let rechte = &state.permission_service;
+    if !rechte
+        .berechtigung_pruefen(actor_id.inner(), von.inner(), "b_client_move")
+        .await?
+    {
+        return Err(SignalingError::ZugriffVerweigert(
+            "Keine Move-Berechtigung".into(),
+        ));
+    }
+
+    let ziel = ChannelRepository::get_by_id(state.db.as_ref(), nach.inner())
+        .await
+        .map_err(|e| SignalingError::intern(e.to_string()))?
+        .ok_or_else(|| SignalingError::NichtGefunden(format!("Kanal {nach}")))?;
+
+    // 1. Momentaufnahme
+    let im_kanal = state.presence.user_ids_in_channel(&von);
+    let mut uebersprungen = Vec::new();
+    let kandidaten: Vec<UserId> = match &request.only_user_ids {
+        Some(auswahl) => {
+            let mut gesehen = HashSet::new();
+            auswahl
+                .iter()
+                .filter(|id| gesehen.insert(**id))
+                .filter(|id| {
+                    let drin = im_kanal.contains(id);
+                    if !drin {
+                        uebersprungen.push(SkippedMove {
+                            user_id: **id,
+                            reason: MoveSkipReason::NotInChannel,
+                        });
+                    }
+                    drin
+                })
+                .copied()
+                .collect()
+        }
+        None => im_kanal,
+    };
+
+    // 2. Move-Power je Benutzer
+    let power = rechte
+        .int_berechtigung_pruefen(actor_id.inner(), von.inner(), "i_client_move_power")
+        .await?
+        .unwrap_or(0);
+    let mut erlaubt = Vec::with_capacity(kandidaten.len());
+    for user_id in kandidaten {
+        let benoetigt = if user_id == actor_id {
+            0
+        } else {
+            rechte
+                .int_berechtigung_pruefen(
+                    user_id.inner(),
+                    von.inner(),
+                    "i_client_needed_move_power",
+                )
+                .await?
+                .unwrap_or(0)
+        };
+        if power >= benoetigt {
+            erlaubt.push(user_id);
+        } else {
+            uebersprungen.push(SkippedMove {
+                user_id,
+                reason: MoveSkipReason::InsufficientPower,
+            });
+        }
+    }
+
+    // 3. Ab hier kein await mehr – die Ausfuehrung ist atomar gegenueber
+    //    anderen Signaling-Handlern
+    let mut verschieben = Vec::with_capacity(erlaubt.len());
+    for user_id in erlaubt {
+        if state.presence.channel_von_client(&user_id) == Some(von) {
+            verschieben.push(user_id);
+        } else {
+            uebersprungen.push(SkippedMove {
+                user_id,
+                reason: MoveSkipReason::NotInChannel,
+            });
+        }
+    }
+
+    if ziel.max_clients > 0 {
+        let belegt = state.presence.user_ids_in_channel(&nach).len();
+        let frei = (ziel.max_clients as usize).saturating_sub(belegt);
+        if verschieben.len() > frei {
+            if !request.allow_partial {
+                return Err(SignalingError::KanalVoll);
+            }
+            for user_id in verschieben.split_off(frei) {
+                uebersprungen.push(SkippedMove {
+                    user_id,
+                    reason: MoveSkipReason::ChannelFull,
+                });
+            }
+        }
+    }
+
+    for user_id in &verschieben {
+        // Wie beim einzelnen Move: verschoben werden gilt als Aktivitaet
+        state.aktivitaet.melden(*user_id);
+        kanal_wechseln(state, *user_id, nach);
+    }
+    for user_id in &verschieben {
+        mitglieder_senden(state, *user_id, nach);
+    }
+
+    if !verschieben.is_empty() {
+        state.broadcaster.zustand_senden(|state_version| {
+            ControlMessage::new(
+                0,
+                ControlPayload::ClientsMoved(ClientsMovedEvent {
+                    user_ids: verschieben.clone(),
+                    from_channel_id: von,
+                    to_channel_id: nach,
+                    reason: request.reason.clone(),
+                    state_version,
+                }),
+            )
+        });
+    }
+    // Erst nach dem atomaren Teil: Sendemodus gilt pro Kanal
+    for user_id in &verschieben {
+        sendemodus_neu_bewerten(state, *user_id, nach).await;
+    }
 
     tracing::info!(
         actor = %actor_id,
-        target = %request.target_user_id,
-        ziel_channel = %request.target_channel_id,
-        "Client verschoben"
+        von_channel = %von,
+        ziel_channel = %nach,
+        verschoben = verschieben.len(),
+        uebersprungen = uebersprungen.len(),
+        grund = ?request.reason,
+        "Clients gemeinsam verschoben"
     );
 
-    ControlMessage::new(request_id, ControlPayload::ClientList)
+    let details = serde_json::json!({
+        "nach_kanal": nach,
+        "verschoben": verschieben,
+        "uebersprungen": uebersprungen,
+        "grund": request.reason,
+    });
+    state
+        .audit_protokollieren(NeuerAuditEintrag::neu(
+            Some(actor_id.inner()),
+            "clients.verschoben",
+            Some("channel"),
+            Some(&von.to_string()),
+            details,
+        ))
+        .await;
+
+    Ok(ClientsMoveAllResponse {
+        moved: verschieben,
+        skipped: uebersprungen,
+    })
 }
 
 /// Verarbeitet Client-Poke (Anklopfen)
@@ -404,5 +825,498 @@ where
         );
     }
 
+    // Wechsel zwischen Senden und Nur-Zuhoeren im aktuellen Kanal
+    if let (Some(senden), Some(channel_id)) = (
+        request.transmit_requested,
+        state.presence.channel_von_client(&user_id),
+    ) {
+        if senden && !senden_erlaubt(state, user_id, channel_id).await {
+            return ControlMessage::error(
+                request_id,
+                ErrorCode::PermissionDenied,
+                "Keine Berechtigung in diesem Channel zu sprechen",
+            );
+        }
+        let nur_hoeren = sendemodus_anwenden(state, user_id, channel_id, Some(!senden)).await;
+        sendemodus_melden(state, user_id, channel_id);
+
+        tracing::debug!(
+            user_id = %user_id,
+            channel_id = %channel_id,
+            nur_hoeren,
+            "Sendemodus gewechselt"
+        );
+    }
+
     ControlMessage::new(request_id, ControlPayload::ClientList)
 }
+
+/// Platzhaltername fuer geloeschte Konten und Kanaele
+pub const GELOESCHT_NAME: &str = "[geloescht]";
+
+/// Verarbeitet eine Namensaufloesung (Benutzer- und Kanal-IDs)
+///
+/// Jeder angemeldete Client sieht alle Benutzer und Kanaele des Servers,
+/// begrenzt wird nur die Anzahl IDs pro Anfrage. Verbundene Benutzer kommen
+/// mit ihrem aktuellen Anzeigenamen; geloeschte, deaktivierte und
+/// unbekannte Konten mit einem Platzhalter.
+pub async fn handle_resolve_ids<U, P, B>(
+    request: ResolveIdsRequest,
+    request_id: u32,
+    state: &Arc<SignalingState<U, P, B>>,
+) -> ControlMessage
+where
+    U: UserRepository + ServerGroupRepository + ChannelRepository + ChatMessageRepository + 'static,
+    P: PermissionRepository + 'static,
+    B: BanRepository + 'static,
+{
+    if request.user_ids.len() + request.channel_ids.len() > MAX_AUFLOESUNG_IDS {
+        return ControlMessage::error(
+            request_id,
+            ErrorCode::InvalidRequest,
+            format!("Hoechstens {MAX_AUFLOESUNG_IDS} IDs pro Anfrage"),
+        );
+    }
+
+    match namen_aufloesen(state, request).await {
+        Ok(antwort) => ControlMessage::new(request_id, ControlPayload::ResolveIdsResponse(antwort)),
+        Err(e) => {
+            tracing::error!(fehler = %e, "Namensaufloesung fehlgeschlagen");
+            ControlMessage::error(request_id, ErrorCode::InternalError, "Interner Fehler")
+        }
+    }
+}
+
+async fn namen_aufloesen<U, P, B>(
+    state: &Arc<SignalingState<U, P, B>>,
+    request: ResolveIdsRequest,
+) -> speakeasy_db::DbResult<ResolveIdsResponse>
+where
+    U: UserRepository + ServerGroupRepository + ChannelRepository + ChatMessageRepository + 'static,
+    P: PermissionRepository + 'static,
+    B: BanRepository + 'static,
+{
+    let mut antwort = ResolveIdsResponse::default();
+
+    let mut gesehen = HashSet::new();
+    for user_id in request.user_ids {
+        if !gesehen.insert(user_id) {
+            continue;
+        }
+        if let Some(presence) = state.presence.client_presence(&user_id) {
+            antwort.users.push(ResolvedUser {
+                user_id,
+                username: presence.username,
+                display_name: presence.display_name,
+                deleted: false,
+            });
+            continue;
+        }
+        let benutzer = if user_id.inner() == GELOESCHTER_BENUTZER {
+            None
+        } else {
+            UserRepository::get_by_id(state.db.as_ref(), user_id.inner()).await?
+        };
+        antwort.users.push(match benutzer {
+            Some(b) if b.is_active => ResolvedUser {
+                user_id,
+                display_name: b.username.clone(),
+                username: b.username,
+                deleted: false,
+            },
+            _ => ResolvedUser {
+                user_id,
+                username: GELOESCHT_NAME.into(),
+                display_name: GELOESCHT_NAME.into(),
+                deleted: true,
+            },
+        });
+    }
+
+    let mut gesehen = HashSet::new();
+    for channel_id in request.channel_ids {
+        if !gesehen.insert(channel_id) {
+            continue;
+        }
+        antwort.channels.push(
+            match ChannelRepository::get_by_id(state.db.as_ref(), channel_id.inner()).await? {
+                Some(kanal) => ResolvedChannel {
+                    channel_id,
+                    name: kanal.name,
+                    deleted: false,
+                },
+                None => ResolvedChannel {
+                    channel_id,
+                    name: GELOESCHT_NAME.into(),
+                    deleted: true,
+                },
+            },
+        );
+    }
+
+    Ok(antwort)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_hilfen::{client_anmelden, moderator, neuer_client, test_state, TestState};
+    use speakeasy_core::{FehlerCode, SpeakeasyError};
+    use speakeasy_db::models::{
+        AuditLogFilter, BerechtigungsWert, BerechtigungsZiel, NeuerBenutzer, NeuerKanal,
+    };
+    use speakeasy_protocol::presenz::PresenzAbbild;
+
+    async fn kanal(state: &TestState, name: &str, max_clients: i64) -> ChannelId {
+        ChannelRepository::create(
+            state.db.as_ref(),
+            NeuerKanal {
+                name,
+                max_clients,
+                ..Default::default()
+            },
+        )
+        .await
+        .map(|k| ChannelId(k.id))
+        .unwrap()
+    }
+
+    async fn moderator_anmelden(state: &TestState, kanal: ChannelId) -> UserId {
+        let user_id = moderator(state).await;
+        client_anmelden(state, user_id, Some(kanal));
+        user_id
+    }
+
+    async fn power_setzen(state: &TestState, user: UserId, kanal: ChannelId, key: &str, wert: i64) {
+        state
+            .db
+            .set_permission(
+                &BerechtigungsZiel::Benutzer(user.inner()),
+                key,
+                BerechtigungsWert::IntLimit(wert),
+                Some(kanal.inner()),
+            )
+            .await
+            .unwrap();
+    }
+
+    fn anfrage(von: ChannelId, nach: ChannelId) -> ClientsMoveAllRequest {
+        ClientsMoveAllRequest {
+            from_channel_id: von,
+            to_channel_id: nach,
+            only_user_ids: None,
+            allow_partial: false,
+            reason: Some("Event".into()),
+        }
+    }
+
+    fn grund_von(antwort: &ClientsMoveAllResponse, user: UserId) -> Option<MoveSkipReason> {
+        antwort
+            .skipped
+            .iter()
+            .find(|s| s.user_id == user)
+            .map(|s| s.reason)
+    }
+
+    #[tokio::test]
+    async fn teilweise_uebersprungen_mit_einem_ereignis() {
+        let state = test_state().await;
+        let lobby = kanal(&state, "Lobby", 0).await;
+        let buehne = kanal(&state, "Buehne", 0).await;
+        let moderator = moderator_anmelden(&state, buehne).await;
+        let a = neuer_client(&state, Some(lobby));
+        let b = neuer_client(&state, Some(lobby));
+        let admin = neuer_client(&state, Some(lobby));
+        let woanders = neuer_client(&state, Some(buehne));
+        power_setzen(&state, moderator, lobby, "i_client_move_power", 50).await;
+        power_setzen(&state, admin, lobby, "i_client_needed_move_power", 75).await;
+        let mut rx = state.broadcaster.client_registrieren(woanders);
+
+        let mut req = anfrage(lobby, buehne);
+        req.only_user_ids = Some(vec![a, b, admin, woanders]);
+        let antwort = clients_alle_verschieben(&state, moderator, req)
+            .await
+            .unwrap();
+
+        assert_eq!(antwort.moved, vec![a, b]);
+        assert_eq!(
+            grund_von(&antwort, admin),
+            Some(MoveSkipReason::InsufficientPower)
+        );
+        assert_eq!(
+            grund_von(&antwort, woanders),
+            Some(MoveSkipReason::NotInChannel)
+        );
+        assert_eq!(state.presence.channel_von_client(&a), Some(buehne));
+        assert_eq!(state.presence.channel_von_client(&admin), Some(lobby));
+
+        // Genau ein Sammel-Ereignis, keine einzelnen ClientMoved
+        let mut ereignisse = vec![];
+        while let Ok(nachricht) = rx.try_recv() {
+            match nachricht.payload {
+                ControlPayload::ClientsMoved(ev) => ereignisse.push(ev),
+                ControlPayload::ClientMoved(_) => panic!("Einzelnes ClientMoved gesendet"),
+                _ => {}
+            }
+        }
+        assert_eq!(ereignisse.len(), 1);
+        assert_eq!(ereignisse[0].user_ids, vec![a, b]);
+        assert_eq!(ereignisse[0].from_channel_id, lobby);
+
+        let eintraege = state
+            .db
+            .list_events(AuditLogFilter {
+                action: Some("clients.verschoben".into()),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(eintraege.len(), 1);
+        assert_eq!(
+            eintraege[0].details["verschoben"].as_array().unwrap().len(),
+            2
+        );
+    }
+
+    #[tokio::test]
+    async fn volles_ziel_lehnt_ab_oder_verschiebt_teilweise() {
+        let state = test_state().await;
+        let lobby = kanal(&state, "Lobby", 0).await;
+        let klein = kanal(&state, "Klein", 2).await;
+        neuer_client(&state, Some(klein));
+        let moderator = neuer_client(&state, Some(lobby));
+        neuer_client(&state, Some(lobby));
+        neuer_client(&state, Some(lobby));
+
+        let fehler = clients_alle_verschieben(&state, moderator, anfrage(lobby, klein))
+            .await
+            .unwrap_err();
+        assert!(matches!(fehler, SignalingError::KanalVoll));
+        assert_eq!(SpeakeasyError::from(fehler).code(), FehlerCode::KanalVoll);
+        assert_eq!(state.presence.user_ids_in_channel(&lobby).len(), 3);
+
+        let mut req = anfrage(lobby, klein);
+        req.allow_partial = true;
+        let antwort = clients_alle_verschieben(&state, moderator, req)
+            .await
+            .unwrap();
+        assert_eq!(antwort.moved.len(), 1);
+        assert_eq!(antwort.skipped.len(), 2);
+        assert!(antwort
+            .skipped
+            .iter()
+            .all(|s| s.reason == MoveSkipReason::ChannelFull));
+        assert_eq!(state.presence.user_ids_in_channel(&klein).len(), 2);
+        assert_eq!(state.presence.user_ids_in_channel(&lobby).len(), 2);
+    }
+
+    #[tokio::test]
+    async fn unbekannter_zielkanal() {
+        let state = test_state().await;
+        let lobby = kanal(&state, "Lobby", 0).await;
+        let moderator = neuer_client(&state, Some(lobby));
+
+        let fehler = clients_alle_verschieben(&state, moderator, anfrage(lobby, ChannelId::new()))
+            .await
+            .unwrap_err();
+        assert_eq!(
+            SpeakeasyError::from(fehler).code(),
+            FehlerCode::NichtGefunden
+        );
+        let fehler = clients_alle_verschieben(&state, moderator, anfrage(lobby, lobby))
+            .await
+            .unwrap_err();
+        assert_eq!(
+            SpeakeasyError::from(fehler).code(),
+            FehlerCode::UngueltigeAnfrage
+        );
+    }
+
+    fn abbild_von(antwort: ControlMessage) -> PresenzAbbild {
+        let ControlPayload::ClientListResponse(liste) = antwort.payload else {
+            panic!("ClientListResponse erwartet");
+        };
+        let mut abbild = PresenzAbbild::neu();
+        abbild.liste_uebernehmen(&liste);
+        abbild
+    }
+
+    #[tokio::test]
+    async fn diff_nach_sammel_move_ergibt_abbild() {
+        let state = test_state().await;
+        let lobby = kanal(&state, "Lobby", 0).await;
+        let buehne = kanal(&state, "Buehne", 0).await;
+        let afk = kanal(&state, "AFK", 0).await;
+        let moderator = neuer_client(&state, Some(buehne));
+        let a = neuer_client(&state, Some(lobby));
+        neuer_client(&state, Some(lobby));
+        neuer_client(&state, Some(lobby));
+
+        // Client schlaeft ein, waehrend verschoben wird
+        let mut abbild = abbild_von(handle_client_list(1, &state).await);
+        clients_alle_verschieben(&state, moderator, anfrage(lobby, buehne))
+            .await
+            .unwrap();
+        client_verschieben(&state, a, afk, Some("idle".into()));
+
+        let ControlPayload::StateDiffResponse(diff) = handle_state_diff(
+            StateDiffRequest {
+                since_version: abbild.version(),
+            },
+            2,
+            &state,
+        )
+        .await
+        .payload
+        else {
+            panic!("StateDiffResponse erwartet");
+        };
+        assert!(!diff.snapshot_required);
+        assert_eq!(diff.events.len(), 2);
+        assert!(abbild.diff_anwenden(&diff).unwrap());
+
+        let neu = abbild_von(handle_client_list(3, &state).await);
+        assert_eq!(abbild, neu);
+        assert_eq!(abbild.kanal_von(&a), Some(afk));
+        assert_eq!(state.presence.user_ids_in_channel(&buehne).len(), 3);
+    }
+
+    fn aufloesung_von(antwort: ControlMessage) -> ResolveIdsResponse {
+        match antwort.payload {
+            ControlPayload::ResolveIdsResponse(antwort) => antwort,
+            andere => panic!("ResolveIdsResponse erwartet, erhalten: {andere:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn namen_mit_platzhaltern_fuer_geloeschte() {
+        let state = test_state().await;
+        let lobby = kanal(&state, "Lobby", 0).await;
+        let verbunden = moderator_anmelden(&state, lobby).await;
+        let offline = UserRepository::create(
+            state.db.as_ref(),
+            NeuerBenutzer {
+                username: "offline",
+                password_hash: "hash",
+            },
+        )
+        .await
+        .unwrap()
+        .id;
+        let deaktiviert = UserRepository::create(
+            state.db.as_ref(),
+            NeuerBenutzer {
+                username: "weg",
+                password_hash: "hash",
+            },
+        )
+        .await
+        .unwrap()
+        .id;
+        UserRepository::delete(state.db.as_ref(), deaktiviert)
+            .await
+            .unwrap();
+
+        let ids = [
+            verbunden,
+            UserId(offline),
+            UserId(deaktiviert),
+            UserId(GELOESCHTER_BENUTZER),
+            UserId::new(),
+            verbunden,
+        ];
+        let antwort = aufloesung_von(
+            handle_resolve_ids(
+                ResolveIdsRequest {
+                    user_ids: ids.to_vec(),
+                    channel_ids: vec![lobby, ChannelId::new()],
+                },
+                1,
+                &state,
+            )
+            .await,
+        );
+
+        let namen: Vec<_> = antwort
+            .users
+            .iter()
+            .map(|b| (b.display_name.as_str(), b.deleted))
+            .collect();
+        assert_eq!(
+            namen,
+            [
+                ("Test", false),
+                ("offline", false),
+                (GELOESCHT_NAME, true),
+                (GELOESCHT_NAME, true),
+                (GELOESCHT_NAME, true),
+            ]
+        );
+        let kanaele: Vec<_> = antwort
+            .channels
+            .iter()
+            .map(|k| (k.name.as_str(), k.deleted))
+            .collect();
+        assert_eq!(kanaele, [("Lobby", false), (GELOESCHT_NAME, true)]);
+    }
+
+    #[tokio::test]
+    async fn zu_viele_ids_werden_abgelehnt() {
+        let state = test_state().await;
+        let request = ResolveIdsRequest {
+            user_ids: (0..MAX_AUFLOESUNG_IDS).map(|_| UserId::new()).collect(),
+            channel_ids: vec![ChannelId::new()],
+        };
+        let antwort = handle_resolve_ids(request, 1, &state).await;
+        let ControlPayload::Error(fehler) = antwort.payload else {
+            panic!("Fehler erwartet");
+        };
+        assert_eq!(fehler.code, ErrorCode::InvalidRequest);
+    }
+
+    #[tokio::test]
+    async fn nickname_aenderung_geht_an_alle() {
+        use crate::handlers::auth_handler::handle_nickname_change;
+        use speakeasy_protocol::control::NicknameChangeRequest;
+
+        let state = test_state().await;
+        let lobby = kanal(&state, "Lobby", 0).await;
+        let buehne = kanal(&state, "Buehne", 0).await;
+        let anna = moderator_anmelden(&state, lobby).await;
+        let woanders = neuer_client(&state, Some(buehne));
+        let mut rx = state.broadcaster.client_registrieren(woanders);
+
+        handle_nickname_change(
+            NicknameChangeRequest {
+                new_nickname: "Anna".into(),
+            },
+            1,
+            anna,
+            &state,
+        )
+        .await;
+
+        let mut ereignisse = vec![];
+        while let Ok(nachricht) = rx.try_recv() {
+            if let ControlPayload::ClientUpdated(ev) = nachricht.payload {
+                ereignisse.push(ev.client);
+            }
+        }
+        assert_eq!(ereignisse.len(), 1);
+        assert_eq!(ereignisse[0].user_id, anna);
+        assert_eq!(ereignisse[0].display_name, "Anna");
+
+        let antwort = aufloesung_von(
+            handle_resolve_ids(
+                ResolveIdsRequest {
+                    user_ids: vec![anna],
+                    channel_ids: vec![],
+                },
+                2,
+                &state,
+            )
+            .await,
+        );
+        assert_eq!(antwort.users[0].display_name, "Anna");
+    }
+}
@@ -0,0 +1,181 @@
+//! Datei-Handler – signierte Download- und Upload-Links
+//!
+//! `FileDownload` delegiert an den [`DateiDienst`](speakeasy_chat::DateiDienst),
+//! `FileUpload` an den [`UploadDienst`](speakeasy_chat::UploadDienst) des
+//! Servers. Die Datei selbst uebertraegt der Client per HTTP mit dem
+//! Datei-Server; ueber die Control-Verbindung gehen nur Link, Groesse und
+//! Checksum. Abgeschlossene Uploads meldet der Datei-Server mit
+//! [`datei_nachricht_melden`](crate::handlers::chat_handler::datei_nachricht_melden).
+//!
+//! Links gibt es nur fuer Kanaele, die es gibt und die der Benutzer betreten
+//! darf (`b_channel_join`); Uploads unterliegen zusaetzlich wie jede
+//! Chat-Nachricht dem Langsam-Modus des Kanals.
+
+use chrono::Utc;
+use speakeasy_chat::UploadAnmeldung;
+use speakeasy_core::{
+    types::{ChannelId, UserId},
+    SpeakeasyError,
+};
+use speakeasy_db::{
+    repository::UserRepository, BanRepository, ChannelRepository, ChatMessageRepository,
+    PermissionRepository, ServerGroupRepository,
+};
+use speakeasy_protocol::control::{
+    ControlMessage, ControlPayload, ErrorCode, FileDownloadRequest, FileDownloadResponse,
+    FileUploadRequest, FileUploadResponse,
+};
+use std::sync::Arc;
+
+use crate::error::{SignalingError, SignalingResult};
+use crate::server_state::SignalingState;
+
+/// Berechtigung, ohne die ein Benutzer keine Dateien eines Kanals sieht
+const KANAL_ZUGRIFF: &str = "b_channel_join";
+
+/// Verarbeitet eine Download-Anfrage
+pub async fn handle_file_download<U, P, B>(
+    request: FileDownloadRequest,
+    request_id: u32,
+    user_id: UserId,
+    state: &Arc<SignalingState<U, P, B>>,
+) -> ControlMessage
+where
+    U: UserRepository + ServerGroupRepository + ChannelRepository + ChatMessageRepository + 'static,
+    P: PermissionRepository + 'static,
+    B: BanRepository + 'static,
+{
+    let Some(dienst) = state.datei_dienst() else {
+        return ControlMessage::fehler(
+            request_id,
+            SpeakeasyError::Konfiguration("Downloads auf diesem Server nicht verfuegbar".into()),
+        );
+    };
+    let file_id = match uuid::Uuid::parse_str(&request.file_id) {
+        Ok(id) => id,
+        Err(_) => {
+            return ControlMessage::error(
+                request_id,
+                ErrorCode::InvalidRequest,
+                "Ungueltige Datei-ID",
+            );
+        }
+    };
+
+    let zugriff = match dienst.kanal_von(file_id).await {
+        Ok(kanal) => kanal_zugriff_pruefen(state, user_id, ChannelId(kanal)).await,
+        Err(e) => return ControlMessage::fehler_mit_kontext(request_id, "Download", e),
+    };
+    if let Err(e) = zugriff {
+        tracing::debug!(user_id = %user_id, file_id = %file_id, fehler = %e, "Download abgelehnt");
+        return ControlMessage::fehler(request_id, e);
+    }
+
+    let angebot = match dienst.link_anfordern(file_id, user_id.inner()).await {
+        Ok(angebot) => angebot,
+        Err(e) => return ControlMessage::fehler_mit_kontext(request_id, "Download", e),
+    };
+    let expires_in_secs = (angebot.expires_at - Utc::now()).num_seconds().max(0) as u64;
+
+    ControlMessage::new(
+        request_id,
+        ControlPayload::FileDownloadResponse(FileDownloadResponse {
+            file_id: angebot.file_id.to_string(),
+            filename: angebot.filename,
+            download_url: angebot.download_url,
+            size_bytes: angebot.size_bytes,
+            sha256: angebot.sha256,
+            expires_in_secs,
+        }),
+    )
+}
+
+/// Verarbeitet eine Upload-Anmeldung
+///
+/// Prueft Dateiname, Pruefsummen-Format und Kontingent und gibt den Link
+/// fuer den `PUT` an den Datei-Server zurueck.
+pub async fn handle_file_upload<U, P, B>(
+    request: FileUploadRequest,
+    request_id: u32,
+    user_id: UserId,
+    state: &Arc<SignalingState<U, P, B>>,
+) -> ControlMessage
+where
+    U: UserRepository + ServerGroupRepository + ChannelRepository + ChatMessageRepository + 'static,
+    P: PermissionRepository + 'static,
+    B: BanRepository + 'static,
+{
+    let Some(dienst) = state.upload_dienst() else {
+        return ControlMessage::fehler(
+            request_id,
+            SpeakeasyError::Konfiguration("Uploads auf diesem Server nicht verfuegbar".into()),
+        );
+    };
+
+    if let Err(e) = kanal_zugriff_pruefen(state, user_id, request.channel_id).await {
+        tracing::debug!(
+            user_id = %user_id,
+            channel_id = %request.channel_id,
+            fehler = %e,
+            "Upload abgelehnt"
+        );
+        return ControlMessage::fehler(request_id, e);
+    }
+    // Die Datei wird als Nachricht gepostet
+    if let Err(e) = crate::langsammodus::chat_pruefen(state, user_id, request.channel_id).await {
+        return ControlMessage::fehler(request_id, e);
+    }
+
+    let anmeldung = UploadAnmeldung {
+        channel_id: request.channel_id.inner(),
+        uploader_id: user_id.inner(),
+        filename: request.filename,
+        mime_type: request
+            .mime_type
+            .unwrap_or_else(|| "application/octet-stream".into()),
+        size_bytes: request.size_bytes,
+        checksum: request.checksum,
+    };
+    let angebot = match dienst.upload_anfordern(anmeldung).await {
+        Ok(angebot) => angebot,
+        Err(e) => return ControlMessage::fehler_mit_kontext(request_id, "Upload", e),
+    };
+    let expires_in_secs = (angebot.expires_at - Utc::now()).num_seconds().max(0) as u64;
+
+    ControlMessage::new(
+        request_id,
+        ControlPayload::FileUploadResponse(FileUploadResponse {
+            file_id: angebot.file_id.to_string(),
+            upload_url: angebot.upload_url,
+            expires_in_secs,
+        }),
+    )
+}
+
+/// Prueft, ob der Kanal existiert und der Benutzer ihn betreten darf
+async fn kanal_zugriff_pruefen<U, P, B>(
+    state: &Arc<SignalingState<U, P, B>>,
+    user_id: UserId,
+    channel_id: ChannelId,
+) -> SignalingResult<()>
+where
+    U: UserRepository + ServerGroupRepository + ChannelRepository + ChatMessageRepository + 'static,
+    P: PermissionRepository + 'static,
+    B: BanRepository + 'static,
+{
+    ChannelRepository::get_by_id(state.db.as_ref(), channel_id.inner())
+        .await
+        .map_err(|e| SignalingError::intern(e.to_string()))?
+        .ok_or_else(|| SignalingError::NichtGefunden(format!("Kanal {channel_id}")))?;
+    let erlaubt = state
+        .permission_service
+        .berechtigung_pruefen(user_id.inner(), channel_id.inner(), KANAL_ZUGRIFF)
+        .await
+        .map_err(|e| SignalingError::intern(e.to_string()))?;
+    if !erlaubt {
+        return Err(SignalingError::ZugriffVerweigert(
+            "Kein Zugriff auf diesen Kanal".into(),
+        ));
+    }
+    Ok(())
+}
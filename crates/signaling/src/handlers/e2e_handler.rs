@@ -0,0 +1,305 @@
+//! E2E-Handler – Schluesselnachrichten zwischen Kanalmitgliedern
+//!
+//! Der Server sieht nur Umschlag und Epoch. Eine `GroupKeyDistribute` nimmt
+//! er nur vom Verteiler des Kanals fuer dessen aktuelle Epoch an (siehe
+//! [`schluesselrotation`](crate::schluesselrotation)) und gibt jedem
+//! Empfaenger nur dessen eigenen Eintrag weiter. `KeyRotationRequest` stoesst
+//! eine neue Epoch an, `KeyRevoke` vom Verteiler geht an den ganzen Kanal.
+
+use speakeasy_core::types::UserId;
+use speakeasy_db::{
+    repository::UserRepository, BanRepository, ChannelRepository, ChatMessageRepository,
+    PermissionRepository, ServerGroupRepository,
+};
+use speakeasy_protocol::control::{ControlMessage, ControlPayload, E2EKeyEnvelope, ErrorCode};
+use speakeasy_protocol::crypto::E2EKeyMessage;
+use std::sync::Arc;
+
+use crate::schluesselrotation::{e2e_aktiv, rotation_anstossen};
+use crate::server_state::SignalingState;
+
+/// Verarbeitet eine E2E-Schluesselnachricht
+///
+/// Gibt nur bei Fehlern eine Antwort zurueck.
+pub fn handle_e2e_key<U, P, B>(
+    envelope: E2EKeyEnvelope,
+    request_id: u32,
+    user_id: UserId,
+    state: &Arc<SignalingState<U, P, B>>,
+) -> Option<ControlMessage>
+where
+    U: UserRepository + ServerGroupRepository + ChannelRepository + ChatMessageRepository + 'static,
+    P: PermissionRepository + 'static,
+    B: BanRepository + 'static,
+{
+    if !e2e_aktiv(state) {
+        return Some(ControlMessage::error(
+            request_id,
+            ErrorCode::InvalidRequest,
+            "E2E-Verschluesselung ist auf diesem Server nicht aktiv",
+        ));
+    }
+    let channel_id = envelope.channel_id;
+    if state.presence.channel_von_client(&user_id) != Some(channel_id) {
+        return Some(ControlMessage::error(
+            request_id,
+            ErrorCode::PermissionDenied,
+            "Nicht Mitglied dieses Kanals",
+        ));
+    }
+    let ist_verteiler = state
+        .e2e
+        .kanal(&channel_id)
+        .is_some_and(|k| k.verteiler == user_id);
+
+    match envelope.message {
+        E2EKeyMessage::GroupKeyDistribute {
+            key_id,
+            epoch,
+            key_algorithm,
+            purpose,
+            mut encrypted_keys,
+            wrapping_algorithm,
+            valid_from_ms,
+            expires_at_ms,
+        } => {
+            let Some(kanal) = state.e2e.kanal(&channel_id).filter(|_| ist_verteiler) else {
+                return Some(ControlMessage::error(
+                    request_id,
+                    ErrorCode::PermissionDenied,
+                    "Nur der Schluesselverteiler darf Schluessel verteilen",
+                ));
+            };
+            if epoch != kanal.epoch {
+                // Verspaetet: inzwischen wurde eine neuere Epoch angefordert
+                return Some(ControlMessage::error(
+                    request_id,
+                    ErrorCode::InvalidRequest,
+                    format!("Veraltete Epoch {epoch}, aktuell ist {}", kanal.epoch),
+                ));
+            }
+
+            let mut zugestellt = 0usize;
+            for empfaenger in state.presence.user_ids_in_channel(&channel_id) {
+                if empfaenger == user_id {
+                    continue;
+                }
+                let Some(eintrag) = encrypted_keys.remove(&empfaenger.inner().to_string()) else {
+                    continue;
+                };
+                let nachricht = E2EKeyMessage::GroupKeyDistribute {
+                    key_id,
+                    epoch,
+                    key_algorithm,
+                    purpose,
+                    encrypted_keys: [(empfaenger.inner().to_string(), eintrag)].into(),
+                    wrapping_algorithm,
+                    valid_from_ms,
+                    expires_at_ms,
+                };
+                state.broadcaster.an_user_senden(
+                    &empfaenger,
+                    ControlMessage::new(
+                        0,
+                        ControlPayload::E2EKey(E2EKeyEnvelope {
+                            channel_id,
+                            message: nachricht,
+                        }),
+                    ),
+                );
+                zugestellt += 1;
+            }
+            tracing::debug!(
+                channel_id = %channel_id,
+                epoch,
+                zugestellt,
+                "E2E-Gruppenschluessel verteilt"
+            );
+            None
+        }
+        E2EKeyMessage::KeyRotationRequest { reason, .. } => {
+            rotation_anstossen(state, channel_id, reason);
+            None
+        }
+        revoke @ E2EKeyMessage::KeyRevoke { .. } => {
+            if !ist_verteiler {
+                return Some(ControlMessage::error(
+                    request_id,
+                    ErrorCode::PermissionDenied,
+                    "Nur der Schluesselverteiler darf Schluessel widerrufen",
+                ));
+            }
+            state.broadcaster.an_channel_ausser_senden(
+                &channel_id,
+                &user_id,
+                ControlMessage::new(
+                    0,
+                    ControlPayload::E2EKey(E2EKeyEnvelope {
+                        channel_id,
+                        message: revoke,
+                    }),
+                ),
+            );
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_hilfen::{client_anmelden, TestAufbau, TestState};
+    use speakeasy_core::types::ChannelId;
+    use speakeasy_crypto::{
+        accept_key_distribution, build_key_distribution, create_group_key, encrypt_audio,
+        generate_member_keypair, rotate_group_key, EpochKeyRing, GroupKeyAlgorithm,
+    };
+
+    use speakeasy_protocol::control::E2EKeyRotationRequiredEvent;
+    use speakeasy_protocol::crypto::KeyRotationReason;
+    use std::time::{Duration, Instant};
+    use tokio::sync::mpsc;
+
+    struct Mitglied {
+        user_id: UserId,
+        privat: [u8; 32],
+        rx: mpsc::Receiver<ControlMessage>,
+    }
+
+    async fn state() -> TestState {
+        TestAufbau::default().crypto_mode("e2e").bauen().await
+    }
+
+    fn beitreten(state: &TestState, kanal: ChannelId) -> Mitglied {
+        let user_id = UserId::new();
+        let rx = state.broadcaster.client_registrieren(user_id);
+        client_anmelden(state, user_id, None);
+        let (privat, oeffentlich) = generate_member_keypair();
+        state.e2e.schluessel_setzen(user_id, oeffentlich);
+        state.presence.channel_beitreten(user_id, kanal);
+        state.broadcaster.channel_beitreten(user_id, kanal);
+        Mitglied {
+            user_id,
+            privat,
+            rx,
+        }
+    }
+
+    fn naechste_rotation(m: &mut Mitglied) -> E2EKeyRotationRequiredEvent {
+        std::iter::from_fn(|| m.rx.try_recv().ok())
+            .find_map(|n| match n.payload {
+                ControlPayload::E2EKeyRotationRequired(ev) => Some(ev),
+                _ => None,
+            })
+            .expect("keine Rotationsaufforderung")
+    }
+
+    fn schluesselnachrichten(m: &mut Mitglied) -> Vec<E2EKeyMessage> {
+        std::iter::from_fn(|| m.rx.try_recv().ok())
+            .filter_map(|n| match n.payload {
+                ControlPayload::E2EKey(umschlag) => Some(umschlag.message),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn umschlag(channel_id: ChannelId, message: E2EKeyMessage) -> E2EKeyEnvelope {
+        E2EKeyEnvelope {
+            channel_id,
+            message,
+        }
+    }
+
+    #[tokio::test]
+    async fn verspaetetes_paket_der_alten_epoch_nach_rotation_entschluesselbar() {
+        let state = state().await;
+        let kanal = ChannelId::new();
+        let mut anna = beitreten(&state, kanal);
+        let mut bert = beitreten(&state, kanal);
+        let kanal_str = kanal.to_string();
+
+        // Epoch 0: Anna ist Verteilerin
+        rotation_anstossen(&state, kanal, KeyRotationReason::MemberJoined);
+        let ev = naechste_rotation(&mut anna);
+        let e0 = create_group_key(&kanal_str, 1, ev.epoch, GroupKeyAlgorithm::Aes256Gcm).unwrap();
+        let verteilung = build_key_distribution(&e0, &ev.members, 0).unwrap();
+        assert!(handle_e2e_key(umschlag(kanal, verteilung), 1, anna.user_id, &state).is_none());
+
+        let mut ring = EpochKeyRing::new(&kanal_str, Duration::from_millis(500));
+        let t0 = Instant::now();
+        let erhalten = schluesselnachrichten(&mut bert);
+        assert_eq!(erhalten.len(), 1);
+        let E2EKeyMessage::GroupKeyDistribute { encrypted_keys, .. } = &erhalten[0] else {
+            panic!("keine Verteilung");
+        };
+        assert_eq!(encrypted_keys.len(), 1, "nur der eigene Eintrag");
+        let k0 =
+            accept_key_distribution(&erhalten[0], &bert.user_id, &bert.privat, &kanal_str).unwrap();
+        ring.install(k0, t0).unwrap();
+
+        // Anna sendet noch mit Epoch 0, waehrend Epoch 1 verteilt wird
+        let unterwegs = encrypt_audio(b"noch epoch 0", &e0, 1, 99).unwrap();
+        rotation_anstossen(&state, kanal, KeyRotationReason::Scheduled);
+        let ev = naechste_rotation(&mut anna);
+        assert_eq!(ev.epoch, 1);
+        let e1 = rotate_group_key(&e0).unwrap();
+        let verteilung = build_key_distribution(&e1, &ev.members, 0).unwrap();
+        assert!(handle_e2e_key(umschlag(kanal, verteilung), 2, anna.user_id, &state).is_none());
+
+        let erhalten = schluesselnachrichten(&mut bert);
+        let k1 =
+            accept_key_distribution(&erhalten[0], &bert.user_id, &bert.privat, &kanal_str).unwrap();
+        ring.install(k1, t0).unwrap();
+        assert_eq!(ring.current_epoch(), Some(1));
+
+        assert_eq!(
+            ring.decrypt_bytes(&unterwegs.to_bytes(), t0 + Duration::from_millis(100))
+                .unwrap(),
+            b"noch epoch 0"
+        );
+        let neu = encrypt_audio(b"epoch 1", &e1, 1, 100).unwrap();
+        assert_eq!(ring.decrypt(&neu, t0).unwrap(), b"epoch 1");
+    }
+
+    #[tokio::test]
+    async fn verteilung_nur_vom_verteiler_fuer_aktuelle_epoch() {
+        let state = state().await;
+        let kanal = ChannelId::new();
+        let mut anna = beitreten(&state, kanal);
+        let bert = beitreten(&state, kanal);
+
+        rotation_anstossen(&state, kanal, KeyRotationReason::MemberJoined);
+        let ev0 = naechste_rotation(&mut anna);
+        rotation_anstossen(&state, kanal, KeyRotationReason::MemberJoined);
+        let ev1 = naechste_rotation(&mut anna);
+
+        let key = create_group_key("k", 1, ev0.epoch, GroupKeyAlgorithm::Aes256Gcm).unwrap();
+        let veraltet = build_key_distribution(&key, &ev0.members, 0).unwrap();
+        let antwort = handle_e2e_key(umschlag(kanal, veraltet), 1, anna.user_id, &state).unwrap();
+        assert!(matches!(antwort.payload, ControlPayload::Error(_)));
+
+        let key = create_group_key("k", 2, ev1.epoch, GroupKeyAlgorithm::Aes256Gcm).unwrap();
+        let fremd = build_key_distribution(&key, &ev1.members, 0).unwrap();
+        let antwort = handle_e2e_key(umschlag(kanal, fremd), 2, bert.user_id, &state).unwrap();
+        let ControlPayload::Error(fehler) = antwort.payload else {
+            panic!("Fehler erwartet");
+        };
+        assert_eq!(fehler.code, ErrorCode::PermissionDenied);
+    }
+
+    #[tokio::test]
+    async fn nachricht_fuer_fremden_kanal_abgelehnt() {
+        let state = state().await;
+        let anna = beitreten(&state, ChannelId::new());
+        let anfrage = E2EKeyMessage::KeyRotationRequest {
+            current_key_id: 1,
+            reason: KeyRotationReason::ManualRequest,
+        };
+        let antwort =
+            handle_e2e_key(umschlag(ChannelId::new(), anfrage), 1, anna.user_id, &state).unwrap();
+        let ControlPayload::Error(fehler) = antwort.payload else {
+            panic!("Fehler erwartet");
+        };
+        assert_eq!(fehler.code, ErrorCode::PermissionDenied);
+    }
+}
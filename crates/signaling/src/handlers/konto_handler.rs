@@ -0,0 +1,125 @@
+//! Konto-Handler – Datenexport und Kontoloeschung in Selbstbedienung
+//!
+//! Beide Nachrichten delegieren an den [`KontoDienst`](speakeasy_chat::KontoDienst)
+//! des Servers. Ohne konfigurierten Dienst werden sie abgelehnt.
+
+use speakeasy_auth::AuthError;
+use speakeasy_core::{types::UserId, SpeakeasyError};
+use speakeasy_db::{
+    repository::UserRepository, BanRepository, ChannelRepository, ChatMessageRepository,
+    PermissionRepository, ServerGroupRepository,
+};
+use speakeasy_protocol::control::{
+    AccountDataExportResponse, AccountDeleteRequest, AccountDeleteResponse,
+    AccountExportReadyEvent, ControlMessage, ControlPayload, ErrorCode,
+};
+use std::sync::Arc;
+
+use crate::server_state::SignalingState;
+
+/// Verarbeitet eine Datenexport-Anfrage
+///
+/// Legt den Export an (hoechstens einer pro Sperrfrist) und antwortet sofort.
+/// Das Archiv entsteht im Hintergrund; danach erhaelt der Benutzer ein
+/// `AccountExportReady` mit dem Einmal-Link.
+pub async fn handle_account_data_export<U, P, B>(
+    request_id: u32,
+    user_id: UserId,
+    state: &Arc<SignalingState<U, P, B>>,
+) -> ControlMessage
+where
+    U: UserRepository + ServerGroupRepository + ChannelRepository + ChatMessageRepository + 'static,
+    P: PermissionRepository + 'static,
+    B: BanRepository + 'static,
+{
+    let Some(dienst) = state.konto_dienst() else {
+        return nicht_verfuegbar(request_id);
+    };
+    let auftrag = match dienst.export_anfordern(user_id.inner(), true).await {
+        Ok(auftrag) => auftrag,
+        Err(e) => return ControlMessage::fehler_mit_kontext(request_id, "Datenexport", e),
+    };
+    let export_id = auftrag.export_id.to_string();
+
+    let dienst = Arc::clone(dienst);
+    let broadcaster = state.broadcaster.clone();
+    tokio::spawn(async move {
+        let nachricht = match dienst.export_erstellen(&auftrag).await {
+            Ok(export) => ControlMessage::new(
+                0,
+                ControlPayload::AccountExportReady(AccountExportReadyEvent {
+                    export_id: export.export_id.to_string(),
+                    download_url: export.download_url,
+                    expires_at: export.expires_at.timestamp(),
+                    size_bytes: export.size_bytes,
+                }),
+            ),
+            Err(e) => {
+                tracing::error!(user_id = %user_id, fehler = %e, "Datenexport fehlgeschlagen");
+                ControlMessage::fehler_mit_kontext(0, "Datenexport", e)
+            }
+        };
+        if !broadcaster.an_user_senden(&user_id, nachricht) {
+            tracing::debug!(user_id = %user_id, "Datenexport fertig, Benutzer nicht mehr verbunden");
+        }
+    });
+
+    ControlMessage::new(
+        request_id,
+        ControlPayload::AccountDataExportResponse(AccountDataExportResponse { export_id }),
+    )
+}
+
+/// Verarbeitet eine Konto-Loeschanfrage
+///
+/// Prueft das Passwort, loescht das Konto und beendet alle Sessions und
+/// API-Tokens. Die Verbindung raeumt der Dispatcher danach ab.
+pub async fn handle_account_delete<U, P, B>(
+    request: AccountDeleteRequest,
+    request_id: u32,
+    user_id: UserId,
+    state: &Arc<SignalingState<U, P, B>>,
+) -> ControlMessage
+where
+    U: UserRepository + ServerGroupRepository + ChannelRepository + ChatMessageRepository + 'static,
+    P: PermissionRepository + 'static,
+    B: BanRepository + 'static,
+{
+    let Some(dienst) = state.konto_dienst() else {
+        return nicht_verfuegbar(request_id);
+    };
+    match state
+        .auth_service
+        .passwort_bestaetigen(user_id.inner(), &request.password_confirmation)
+        .await
+    {
+        Ok(()) => {}
+        Err(AuthError::UngueltigeAnmeldedaten) => {
+            tracing::warn!(user_id = %user_id, "Kontoloeschung: falsches Passwort");
+            return ControlMessage::error(
+                request_id,
+                ErrorCode::InvalidCredentials,
+                "Passwort ist falsch",
+            );
+        }
+        Err(e) => return ControlMessage::fehler(request_id, e),
+    }
+
+    if let Err(e) = dienst.konto_loeschen(user_id.inner(), None).await {
+        tracing::error!(user_id = %user_id, fehler = %e, "Kontoloeschung fehlgeschlagen");
+        return ControlMessage::fehler_mit_kontext(request_id, "Kontoloeschung", e);
+    }
+    state.auth_service.zugaenge_beenden(user_id.inner()).await;
+
+    ControlMessage::new(
+        request_id,
+        ControlPayload::AccountDeleteResponse(AccountDeleteResponse { success: true }),
+    )
+}
+
+fn nicht_verfuegbar(request_id: u32) -> ControlMessage {
+    ControlMessage::fehler(
+        request_id,
+        SpeakeasyError::Konfiguration("Kontoverwaltung auf diesem Server nicht verfuegbar".into()),
+    )
+}
@@ -7,6 +7,9 @@ pub mod auth_handler;
 pub mod channel_handler;
 pub mod chat_handler;
 pub mod client_handler;
+pub mod datei_handler;
+pub mod e2e_handler;
+pub mod konto_handler;
 pub mod permission_handler;
 pub mod server_handler;
 pub mod voice_handler;
@@ -1,17 +1,20 @@
-//! Permission-Handler – List, Add, Remove
+//! Permission-Handler – List, Add, Remove, EffectivePermissions
 //!
 //! Verwaltung von Berechtigungen fuer Benutzer und Gruppen.
-//! Alle Operationen erfordern Admin-Rechte (b_permission_modify).
+//! Aendernde Operationen erfordern Admin-Rechte (b_permission_modify), die
+//! Diagnose der effektiven Berechtigungen `b_permission_view`.
 
 use speakeasy_core::types::{ChannelId, UserId};
 use speakeasy_db::{
     models::{BerechtigungsWert, BerechtigungsZiel, TriState},
+    permissions::BerechtigungsSpur,
     repository::UserRepository,
     BanRepository, ChannelRepository, ChatMessageRepository, PermissionRepository,
     ServerGroupRepository,
 };
 use speakeasy_protocol::control::{
-    ControlMessage, ControlPayload, ErrorCode, PermissionAddRequest, PermissionEntry,
+    ControlMessage, ControlPayload, EffectivePermissionEntry, EffectivePermissionsRequest,
+    EffectivePermissionsResponse, ErrorCode, PermissionAddRequest, PermissionEntry,
     PermissionListResponse, PermissionRemoveRequest, PermissionValue,
 };
 use std::sync::Arc;
@@ -274,3 +277,244 @@ where
         }),
     )
 }
+
+/// Wandelt eine Berechtigungs-Spur in einen Protokoll-Eintrag um
+fn spur_zu_eintrag(spur: BerechtigungsSpur) -> EffectivePermissionEntry {
+    let entscheidend = spur.entscheidend;
+    EffectivePermissionEntry {
+        permission: spur.permission_key,
+        value: entscheidend.as_ref().map(|e| db_zu_protokoll_wert(&e.wert)),
+        source: entscheidend.as_ref().map(|e| e.stufe.to_string()),
+        scope: entscheidend.map(|e| e.stufe.geltungsbereich().to_string()),
+    }
+}
+
+/// Verarbeitet die Abfrage effektiver Berechtigungen
+///
+/// Erfordert `b_permission_view`-Berechtigung. Liefert fuer jeden Key des
+/// Berechtigungskatalogs den effektiven Wert und die entscheidende Regel.
+pub async fn handle_effective_permissions<U, P, B>(
+    request: EffectivePermissionsRequest,
+    request_id: u32,
+    actor_id: UserId,
+    state: &Arc<SignalingState<U, P, B>>,
+) -> ControlMessage
+where
+    U: UserRepository + ServerGroupRepository + ChannelRepository + ChatMessageRepository + 'static,
+    P: PermissionRepository + 'static,
+    B: BanRepository + 'static,
+{
+    // Berechtigung pruefen: b_permission_view
+    let root = ChannelId(uuid::Uuid::nil());
+    match state
+        .permission_service
+        .berechtigung_pruefen(actor_id.inner(), root.inner(), "b_permission_view")
+        .await
+    {
+        Ok(true) => {}
+        Ok(false) => {
+            return ControlMessage::error(
+                request_id,
+                ErrorCode::PermissionDenied,
+                "Keine Berechtigung zum Einsehen effektiver Permissions",
+            );
+        }
+        // Diagnose legt fremde Rechte offen: bei Fehlern nicht durchlassen
+        Err(e) => {
+            tracing::error!("Berechtigungspruefung fehlgeschlagen: {}", e);
+            return ControlMessage::error(request_id, ErrorCode::InternalError, "Interner Fehler");
+        }
+    }
+
+    match UserRepository::get_by_id(state.db.as_ref(), request.user_id.inner()).await {
+        Ok(Some(_)) => {}
+        Ok(None) => {
+            return ControlMessage::error(
+                request_id,
+                ErrorCode::NotFound,
+                "Benutzer nicht gefunden",
+            );
+        }
+        Err(e) => {
+            tracing::error!("Benutzer laden fehlgeschlagen: {}", e);
+            return ControlMessage::error(request_id, ErrorCode::InternalError, "Interner Fehler");
+        }
+    }
+
+    let kanal = request.channel_id.unwrap_or(root);
+    match state
+        .permission_service
+        .berechtigungen_nachverfolgen(request.user_id.inner(), kanal.inner())
+        .await
+    {
+        Ok(spuren) => ControlMessage::new(
+            request_id,
+            ControlPayload::EffectivePermissionsResponse(EffectivePermissionsResponse {
+                user_id: request.user_id,
+                channel_id: request.channel_id,
+                permissions: spuren.into_iter().map(spur_zu_eintrag).collect(),
+            }),
+        ),
+        Err(e) => {
+            tracing::error!("Effektive Permissions laden fehlgeschlagen: {}", e);
+            ControlMessage::error(request_id, ErrorCode::InternalError, "Interner Fehler")
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_hilfen::{test_state, TestState};
+    use speakeasy_db::{
+        models::{NeueServerGruppe, NeuerBenutzer, NeuerKanal},
+        SqliteDb,
+    };
+
+    async fn state() -> (TestState, Arc<SqliteDb>) {
+        let state = test_state().await;
+        let db = Arc::clone(&state.db);
+        (state, db)
+    }
+
+    async fn benutzer(db: &SqliteDb, name: &str) -> UserId {
+        let user = UserRepository::create(
+            db,
+            NeuerBenutzer {
+                username: name,
+                password_hash: "hash",
+            },
+        )
+        .await
+        .unwrap();
+        UserId(user.id)
+    }
+
+    fn eintrag<'a>(
+        antwort: &'a EffectivePermissionsResponse,
+        key: &str,
+    ) -> &'a EffectivePermissionEntry {
+        antwort
+            .permissions
+            .iter()
+            .find(|e| e.permission == key)
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn effektive_berechtigungen_nennen_gewinnende_regel() {
+        let (state, db) = state().await;
+        let moderator = benutzer(&db, "moderator").await;
+        let ziel = benutzer(&db, "ziel").await;
+        let kanal = ChannelRepository::create(
+            db.as_ref(),
+            NeuerKanal {
+                name: "Uploads",
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        let gruppe = ServerGroupRepository::create(
+            db.as_ref(),
+            NeueServerGruppe {
+                name: "Mitglied",
+                priority: 10,
+                is_default: false,
+            },
+        )
+        .await
+        .unwrap();
+        ServerGroupRepository::add_member(db.as_ref(), gruppe.id, ziel.inner())
+            .await
+            .unwrap();
+
+        let regeln = [
+            (
+                BerechtigungsZiel::ServerGruppe(gruppe.id),
+                "i_upload_limit",
+                BerechtigungsWert::IntLimit(50),
+                None,
+            ),
+            (
+                BerechtigungsZiel::KanalDefault(kanal.id),
+                "i_upload_limit",
+                BerechtigungsWert::IntLimit(0),
+                Some(kanal.id),
+            ),
+            (
+                BerechtigungsZiel::ServerGruppe(gruppe.id),
+                "b_client_poke",
+                BerechtigungsWert::TriState(TriState::Deny),
+                None,
+            ),
+        ];
+        for (ziel, key, wert, kanal_id) in regeln {
+            PermissionRepository::set_permission(db.as_ref(), &ziel, key, wert, kanal_id)
+                .await
+                .unwrap();
+        }
+
+        let request = EffectivePermissionsRequest {
+            user_id: ziel,
+            channel_id: Some(ChannelId(kanal.id)),
+        };
+        let antwort = handle_effective_permissions(request, 3, moderator, &state).await;
+        let ControlPayload::EffectivePermissionsResponse(antwort) = antwort.payload else {
+            panic!("Erwartet EffectivePermissionsResponse");
+        };
+
+        // Kanal-Default schlaegt die Server-Gruppe
+        let upload = eintrag(&antwort, "i_upload_limit");
+        assert!(matches!(upload.value, Some(PermissionValue::IntLimit(0))));
+        assert_eq!(upload.source.as_deref(), Some("KanalDefault"));
+        assert_eq!(upload.scope.as_deref(), Some("kanal"));
+
+        let poke = eintrag(&antwort, "b_client_poke");
+        assert!(matches!(poke.value, Some(PermissionValue::Deny)));
+        assert_eq!(poke.source.as_deref(), Some("ServerGruppe(Mitglied)"));
+        assert_eq!(poke.scope.as_deref(), Some("server"));
+
+        let join = eintrag(&antwort, "b_channel_join");
+        assert!(join.value.is_none());
+        assert!(join.source.is_none());
+    }
+
+    #[tokio::test]
+    async fn effektive_berechtigungen_erfordern_b_permission_view() {
+        let (state, db) = state().await;
+        let gast = benutzer(&db, "gast").await;
+
+        let request = EffectivePermissionsRequest {
+            user_id: UserId::new(),
+            channel_id: None,
+        };
+        let antwort = handle_effective_permissions(request, 4, gast, &state).await;
+        let ControlPayload::Error(fehler) = antwort.payload else {
+            panic!("Erwartet Fehler");
+        };
+        assert_eq!(fehler.code, ErrorCode::NotFound);
+
+        // Server-Default verbietet die Diagnose fuer alle ohne eigene Regel
+        PermissionRepository::set_permission(
+            db.as_ref(),
+            &BerechtigungsZiel::ServerDefault,
+            "b_permission_view",
+            BerechtigungsWert::TriState(TriState::Deny),
+            None,
+        )
+        .await
+        .unwrap();
+        state.permission_service.cache_komplett_invalidieren().await;
+
+        let request = EffectivePermissionsRequest {
+            user_id: gast,
+            channel_id: None,
+        };
+        let antwort = handle_effective_permissions(request, 5, gast, &state).await;
+        let ControlPayload::Error(fehler) = antwort.payload else {
+            panic!("Erwartet Fehler");
+        };
+        assert_eq!(fehler.code, ErrorCode::PermissionDenied);
+    }
+}
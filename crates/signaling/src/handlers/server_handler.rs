@@ -1,20 +1,24 @@
-//! Server-Handler – Info, Edit, Stop
+//! Server-Handler – Info, Edit, Stop, angekuendigte Trennungen abbrechen,
+//! Sitzungsmitschnitte freigeben
 //!
 //! Server-Informationen abrufen und bearbeiten. Alle schreibenden Operationen
 //! erfordern Admin-Berechtigungen (b_server_modify / b_server_stop).
 
 use speakeasy_core::types::{ChannelId, UserId};
 use speakeasy_db::{
-    repository::UserRepository, BanRepository, ChannelRepository, ChatMessageRepository,
-    PermissionRepository, ServerGroupRepository,
+    models::NeuerAuditEintrag, repository::UserRepository, AuditLogRepository, BanRepository,
+    ChannelRepository, ChatMessageRepository, PermissionRepository, ServerGroupRepository,
 };
 use speakeasy_protocol::control::{
-    ControlMessage, ControlPayload, ErrorCode, ServerEditRequest, ServerInfoResponse,
-    ServerStopRequest,
+    CancelPendingDisconnectRequest, ControlMessage, ControlPayload, ErrorCode,
+    PendingDisconnectKind, ServerEditRequest, ServerInfoResponse, ServerStopRequest,
+    SessionCaptureRequest, SessionCaptureResponse, MAX_TRENNUNGSFRIST_SEK,
 };
 use std::sync::Arc;
+use std::time::Duration;
 
 use crate::server_state::SignalingState;
+use crate::trennung::{self, TrennungsZiel};
 
 /// Verarbeitet Server-Info-Anfrage
 pub async fn handle_server_info<U, P, B>(
@@ -27,18 +31,19 @@ where
     B: BanRepository + 'static,
 {
     let current_clients = state.presence.online_anzahl() as u32;
+    let einstellungen = state.einstellungen.aktuell();
 
     ControlMessage::new(
         request_id,
         ControlPayload::ServerInfoResponse(ServerInfoResponse {
             server_id: state.config.server_id,
-            name: state.config.server_name.clone(),
-            welcome_message: state.config.welcome_message.clone(),
-            max_clients: state.config.max_clients,
+            name: einstellungen.name,
+            welcome_message: einstellungen.willkommensnachricht,
+            max_clients: einstellungen.max_clients,
             current_clients,
             version: env!("CARGO_PKG_VERSION").to_string(),
             uptime_secs: state.uptime_sek(),
-            host_message: None,
+            host_message: einstellungen.host_nachricht,
         }),
     )
 }
@@ -77,9 +82,19 @@ where
         Ok(true) => {}
     }
 
-    // Server-Konfiguration ist Arc<SignalingConfig> – im aktuellen Design
-    // nicht zur Laufzeit aenderbar (wuerde Arc<RwLock<...>> erfordern).
-    // Wir loggen die gewuenschten Aenderungen und bestaetigen.
+    // AFK-Richtlinie ist als einziger Teil zur Laufzeit aenderbar
+    if request.afk_timeout_secs.is_some() || request.afk_channel_id.is_some() {
+        state.afk.richtlinie_aendern(
+            request
+                .afk_timeout_secs
+                .map(|s| std::time::Duration::from_secs(s as u64)),
+            request.afk_channel_id,
+        );
+    }
+
+    // Name, Willkommensnachricht usw. werden ueber den Commander in
+    // `server_settings` gespeichert und von dort uebernommen. Hier werden
+    // die gewuenschten Aenderungen nur geloggt und bestaetigt.
     tracing::info!(
         actor = %actor_id,
         neuer_name = ?request.name,
@@ -89,21 +104,20 @@ where
 
     // Als Bestaetigung aktuelle Server-Info senden
     let current_clients = state.presence.online_anzahl() as u32;
+    let einstellungen = state.einstellungen.aktuell();
     ControlMessage::new(
         request_id,
         ControlPayload::ServerInfoResponse(ServerInfoResponse {
             server_id: state.config.server_id,
-            name: request
-                .name
-                .unwrap_or_else(|| state.config.server_name.clone()),
+            name: request.name.unwrap_or(einstellungen.name),
             welcome_message: request
                 .welcome_message
-                .or_else(|| state.config.welcome_message.clone()),
-            max_clients: request.max_clients.unwrap_or(state.config.max_clients),
+                .or(einstellungen.willkommensnachricht),
+            max_clients: request.max_clients.unwrap_or(einstellungen.max_clients),
             current_clients,
             version: env!("CARGO_PKG_VERSION").to_string(),
             uptime_secs: state.uptime_sek(),
-            host_message: request.host_message,
+            host_message: request.host_message.or(einstellungen.host_nachricht),
         }),
     )
 }
@@ -111,7 +125,9 @@ where
 /// Verarbeitet Server-Stop-Anfrage
 ///
 /// Erfordert `b_server_stop`-Berechtigung. Sendet eine Benachrichtigung
-/// an alle Clients und signalisiert dem Server-Task den Shutdown.
+/// an alle Clients und signalisiert dem Server-Task den Shutdown. Mit
+/// `grace_secs` wird der Stopp mit Countdown angekuendigt und kann bis zum
+/// Ablauf abgebrochen werden (siehe [`crate::trennung`]).
 pub async fn handle_server_stop<U, P, B>(
     request: ServerStopRequest,
     request_id: u32,
@@ -120,7 +136,12 @@ pub async fn handle_server_stop<U, P, B>(
     shutdown_tx: &tokio::sync::watch::Sender<bool>,
 ) -> ControlMessage
 where
-    U: UserRepository + ServerGroupRepository + ChannelRepository + ChatMessageRepository + 'static,
+    U: UserRepository
+        + ServerGroupRepository
+        + ChannelRepository
+        + ChatMessageRepository
+        + AuditLogRepository
+        + 'static,
     P: PermissionRepository + 'static,
     B: BanRepository + 'static,
 {
@@ -144,6 +165,27 @@ where
         Ok(true) => {}
     }
 
+    if let Some(frist) = request.grace_secs.filter(|s| *s > 0) {
+        if frist > MAX_TRENNUNGSFRIST_SEK {
+            return ControlMessage::error(
+                request_id,
+                ErrorCode::InvalidRequest,
+                format!("Frist darf hoechstens {MAX_TRENNUNGSFRIST_SEK} Sekunden betragen"),
+            );
+        }
+        let meldung = trennung::ankuendigen(
+            state,
+            TrennungsZiel::Server {
+                shutdown_tx: shutdown_tx.clone(),
+            },
+            request.reason,
+            actor_id,
+            Duration::from_secs(frist.into()),
+        )
+        .await;
+        return ControlMessage::new(request_id, ControlPayload::PendingDisconnectNotice(meldung));
+    }
+
     let grund = request.reason.as_deref().unwrap_or("Server wird gestoppt");
     let delay = request.delay_secs;
 
@@ -172,13 +214,14 @@ where
         tracing::info!("Shutdown-Signal gesendet");
     });
 
+    let einstellungen = state.einstellungen.aktuell();
     ControlMessage::new(
         request_id,
         ControlPayload::ServerInfoResponse(ServerInfoResponse {
             server_id: state.config.server_id,
-            name: state.config.server_name.clone(),
+            name: einstellungen.name,
             welcome_message: None,
-            max_clients: state.config.max_clients,
+            max_clients: einstellungen.max_clients,
             current_clients: state.presence.online_anzahl() as u32,
             version: env!("CARGO_PKG_VERSION").to_string(),
             uptime_secs: state.uptime_sek(),
@@ -186,3 +229,143 @@ where
         }),
     )
 }
+
+/// Bricht einen angekuendigten Kick oder Server-Stopp ab
+///
+/// Erfordert dieselbe Berechtigung wie das Ausloesen; bei Kicks im
+/// aktuellen Kanal des Abbrechenden, beim Server-Stopp serverweit.
+pub async fn handle_cancel_pending_disconnect<U, P, B>(
+    request: CancelPendingDisconnectRequest,
+    request_id: u32,
+    actor_id: UserId,
+    state: &Arc<SignalingState<U, P, B>>,
+) -> ControlMessage
+where
+    U: UserRepository
+        + ServerGroupRepository
+        + ChannelRepository
+        + ChatMessageRepository
+        + AuditLogRepository
+        + 'static,
+    P: PermissionRepository + 'static,
+    B: BanRepository + 'static,
+{
+    let Some(offen) = state.trennungen.offen(&request.action_id) else {
+        return ControlMessage::error(
+            request_id,
+            ErrorCode::NotFound,
+            "Keine angekuendigte Trennung mit dieser ID",
+        );
+    };
+
+    let root = ChannelId(uuid::Uuid::nil());
+    let kanal = match offen.art() {
+        PendingDisconnectKind::ServerStop => root,
+        PendingDisconnectKind::Kick | PendingDisconnectKind::ChannelKick => {
+            state.presence.channel_von_client(&actor_id).unwrap_or(root)
+        }
+    };
+    match state
+        .permission_service
+        .berechtigung_pruefen(actor_id.inner(), kanal.inner(), offen.berechtigung())
+        .await
+    {
+        Ok(false) => {
+            return ControlMessage::error(
+                request_id,
+                ErrorCode::PermissionDenied,
+                "Keine Berechtigung zum Abbrechen",
+            );
+        }
+        Err(e) => {
+            tracing::error!("Berechtigungspruefung fehlgeschlagen: {}", e);
+        }
+        Ok(true) => {}
+    }
+
+    match trennung::abbrechen(state, &request.action_id, actor_id).await {
+        Some(ereignis) => ControlMessage::new(
+            request_id,
+            ControlPayload::PendingDisconnectCancelled(ereignis),
+        ),
+        // Zwischenzeitlich ausgefuehrt oder verfallen
+        None => ControlMessage::error(
+            request_id,
+            ErrorCode::NotFound,
+            "Keine angekuendigte Trennung mit dieser ID",
+        ),
+    }
+}
+
+/// Gibt den Sitzungsmitschnitt fuer einen Benutzer frei oder beendet ihn
+///
+/// Erfordert `b_server_modify`. Der Mitschnitt beginnt erst beim naechsten
+/// Login des Benutzers und nur mit dessen Einwilligung (siehe
+/// [`crate::mitschnitt`]).
+pub async fn handle_session_capture<U, P, B>(
+    request: SessionCaptureRequest,
+    request_id: u32,
+    actor_id: UserId,
+    state: &Arc<SignalingState<U, P, B>>,
+) -> ControlMessage
+where
+    U: UserRepository
+        + ServerGroupRepository
+        + ChannelRepository
+        + ChatMessageRepository
+        + AuditLogRepository
+        + 'static,
+    P: PermissionRepository + 'static,
+    B: BanRepository + 'static,
+{
+    let root = ChannelId(uuid::Uuid::nil());
+    match state
+        .permission_service
+        .berechtigung_pruefen(actor_id.inner(), root.inner(), "b_server_modify")
+        .await
+    {
+        Ok(false) => {
+            return ControlMessage::error(
+                request_id,
+                ErrorCode::PermissionDenied,
+                "Keine Berechtigung fuer Sitzungsmitschnitte",
+            );
+        }
+        Err(e) => {
+            tracing::error!("Berechtigungspruefung fehlgeschlagen: {}", e);
+        }
+        Ok(true) => {}
+    }
+
+    state
+        .mitschnitte
+        .freigeben(request.user_id, request.enabled);
+    tracing::info!(
+        actor = %actor_id,
+        user_id = %request.user_id,
+        aktiv = request.enabled,
+        "Sitzungsmitschnitt-Freigabe geaendert"
+    );
+    state
+        .audit_protokollieren(NeuerAuditEintrag::neu(
+            Some(actor_id.inner()),
+            if request.enabled {
+                "mitschnitt.freigegeben"
+            } else {
+                "mitschnitt.beendet"
+            },
+            Some("user"),
+            Some(&request.user_id.to_string()),
+            serde_json::json!({}),
+        ))
+        .await;
+
+    ControlMessage::new(
+        request_id,
+        ControlPayload::SessionCaptureResponse(SessionCaptureResponse {
+            user_id: request.user_id,
+            enabled: request.enabled,
+            storage_disabled: !state.mitschnitte.speichert(),
+        }),
+    )
+}
@@ -1,21 +1,54 @@
-//! Voice-Handler – VoiceInit, VoiceReady, VoiceDisconnect
+//! Voice-Handler – VoiceInit, VoiceReady, VoiceDisconnect, VoiceStats
 //!
 //! UDP Port Negotiation und SSRC-Zuweisung fuer Voice-Verbindungen.
-//! Koordiniert den Handshake zwischen TCP-Kontrollebene und UDP-Voice-Layer.
+//! Koordiniert den Handshake zwischen TCP-Kontrollebene und UDP-Voice-Layer
+//! und tauscht Empfangsstatistiken fuer die Verlustanzeige aus.
+//!
+//! VoiceInit ist idempotent: geht die VoiceReady-Antwort verloren und der
+//! Client wiederholt die Anfrage, bestaetigt der Server die bestehende
+//! Sitzung statt eine weitere SSRC zu vergeben. Erst `force_new` baut die
+//! Sitzung ab und beginnt eine neue.
+//!
+//! Bietet der Client Resequenzierung an, schreibt der Router die Header
+//! weitergeleiteter Pakete um, damit serverseitig verworfene Pakete keine
+//! Luecken hinterlassen. Im E2E-Modus bleibt der Header unberuehrt; die
+//! verworfenen Pakete meldet dann die Antwort auf den VoiceStats-Bericht.
+//!
+//! Die Opus-Konfiguration handelt der Server gegen seine `CodecRichtlinie`
+//! aus: Presets ausserhalb der Richtlinie und zu hohe Bitraten werden
+//! herabgestuft, nie abgelehnt.
+//!
+//! Im E2E-Modus meldet der Client mit VoiceInit seinen oeffentlichen
+//! X25519-Schluessel. Neu gemeldete und beim Abbau entfernte Schluessel
+//! stossen eine Rotation des Gruppenschluessels im Kanal an.
 
-use speakeasy_core::types::UserId;
+use speakeasy_core::types::{ChannelId, UserId};
 use speakeasy_db::{
     repository::UserRepository, BanRepository, ChannelRepository, ChatMessageRepository,
     PermissionRepository, ServerGroupRepository,
 };
+use speakeasy_protocol::codec::{
+    AudioPreset, CodecNegotiationRequest, CodecNegotiationResponse, NegotiationStatus,
+};
 use speakeasy_protocol::control::{
-    ControlMessage, ControlPayload, VoiceDisconnectRequest, VoiceInitRequest, VoiceReadyResponse,
+    ClientVoiceUpdatedEvent, ControlMessage, ControlPayload, ErrorCode, SsrcReceiveStats,
+    SsrcSender, SsrcSuppressed, VoiceDisconnectRequest, VoiceInitRequest, VoiceQualityReason,
+    VoiceQualityUpdate, VoiceReadyResponse, VoiceStatsReport, VoiceStatsResponse,
 };
+use speakeasy_protocol::crypto::{CryptoMode, KeyRotationReason};
+use speakeasy_protocol::voice::{
+    hello_nonce_kodieren, verlust_rate, AudioCodec, HELLO_NONCE_LAENGE,
+};
+use speakeasy_voice::congestion::{BitrateAnpassung, CongestionAktion};
+use speakeasy_voice::{Resequenzierung, VoiceState};
 use std::net::SocketAddr;
 use std::sync::atomic::{AtomicU32, Ordering};
 use std::sync::Arc;
+use uuid::Uuid;
 
-use crate::server_state::SignalingState;
+use crate::handlers::auth_handler::ban_abgelehnt;
+use crate::schluesselrotation::{e2e_aktiv, rotation_anstossen};
+use crate::server_state::{CodecRichtlinie, SignalingState};
 
 /// Globaler SSRC-Zaehler (atomar, thread-safe)
 ///
@@ -27,10 +60,280 @@ fn naechste_ssrc() -> u32 {
     SSRC_ZAEHLER.fetch_add(1, Ordering::Relaxed)
 }
 
+/// Weist eine SSRC zu, die keinem anderen Client gehoert
+///
+/// Nach einem Ueberlauf des Zaehlers koennten noch belegte SSRCs erneut
+/// vergeben werden; diese werden uebersprungen.
+pub(crate) fn freie_ssrc(voice_state: &VoiceState) -> u32 {
+    loop {
+        let ssrc = naechste_ssrc();
+        if ssrc != 0 && !voice_state.ssrc_belegt(ssrc) {
+            return ssrc;
+        }
+    }
+}
+
+/// Erzeugt eine zufaellige Nonce fuer das Hello des Clients
+///
+/// Zwei v4-UUIDs liefern zusammen 244 Zufallsbits.
+fn hello_nonce() -> Vec<u8> {
+    let mut nonce = Vec::with_capacity(HELLO_NONCE_LAENGE);
+    nonce.extend_from_slice(Uuid::new_v4().as_bytes());
+    nonce.extend_from_slice(Uuid::new_v4().as_bytes());
+    nonce
+}
+
+/// Teilt den uebrigen Mitgliedern eines Kanals die SSRC eines Benutzers mit
+///
+/// `ssrc = None` entfernt den Benutzer aus der Zuordnung der Empfaenger
+/// (Kanal oder Voice verlassen). Der Sendemodus (Nur-Zuhoeren) wird aus der
+/// Presence uebernommen, damit Wechsel ueber dasselbe Event ankommen.
+pub(crate) fn ssrc_melden<U, P, B>(
+    state: &SignalingState<U, P, B>,
+    user_id: UserId,
+    channel_id: ChannelId,
+    ssrc: Option<u32>,
+) where
+    U: UserRepository + ServerGroupRepository + ChannelRepository + ChatMessageRepository + 'static,
+    P: PermissionRepository + 'static,
+    B: BanRepository + 'static,
+{
+    state.broadcaster.an_channel_ausser_senden(
+        &channel_id,
+        &user_id,
+        ControlMessage::new(
+            0,
+            ControlPayload::ClientVoiceUpdated(ClientVoiceUpdatedEvent {
+                user_id,
+                channel_id,
+                ssrc,
+                listen_only: state
+                    .presence
+                    .client_presence(&user_id)
+                    .is_some_and(|p| p.nur_hoeren),
+            }),
+        ),
+    );
+}
+
+/// Legt den Sendemodus eines Clients in einem Kanal fest
+///
+/// Nur-Zuhoeren gilt, wenn der Client es wuenscht oder ihm `b_voice_transmit`
+/// im Kanal verweigert ist. `gewuenscht = None` behaelt die bisherige Wahl
+/// des Clients bei (Kanalwechsel durch Moderatoren oder AFK). Der Modus wird
+/// in Presence und Voice-State gesetzt; Pakete eines Nur-Zuhoerers verwirft
+/// der Voice-Server. Gibt den effektiven Modus zurueck (`true` = Nur-Zuhoeren).
+pub(crate) async fn sendemodus_anwenden<U, P, B>(
+    state: &SignalingState<U, P, B>,
+    user_id: UserId,
+    channel_id: ChannelId,
+    gewuenscht: Option<bool>,
+) -> bool
+where
+    U: UserRepository + ServerGroupRepository + ChannelRepository + ChatMessageRepository + 'static,
+    P: PermissionRepository + 'static,
+    B: BanRepository + 'static,
+{
+    let gewuenscht = gewuenscht.unwrap_or_else(|| {
+        state
+            .presence
+            .client_presence(&user_id)
+            .is_some_and(|p| p.nur_hoeren_gewuenscht)
+    });
+    let nur_hoeren = gewuenscht || !senden_erlaubt(state, user_id, channel_id).await;
+
+    state
+        .presence
+        .sendemodus_setzen(&user_id, gewuenscht, nur_hoeren);
+    state.voice_state.senden_erlauben(&user_id, !nur_hoeren);
+    nur_hoeren
+}
+
+/// Prueft `b_voice_transmit` eines Clients im Kanal
+pub(crate) async fn senden_erlaubt<U, P, B>(
+    state: &SignalingState<U, P, B>,
+    user_id: UserId,
+    channel_id: ChannelId,
+) -> bool
+where
+    U: UserRepository + ServerGroupRepository + ChannelRepository + ChatMessageRepository + 'static,
+    P: PermissionRepository + 'static,
+    B: BanRepository + 'static,
+{
+    match state
+        .permission_service
+        .berechtigung_pruefen(user_id.inner(), channel_id.inner(), "b_voice_transmit")
+        .await
+    {
+        Ok(erlaubt) => erlaubt,
+        Err(e) => {
+            // Wie beim Beitritt: Fehler der Pruefung blockieren nicht
+            tracing::error!("Berechtigungspruefung fehlgeschlagen: {}", e);
+            true
+        }
+    }
+}
+
+/// Bewertet den Sendemodus nach einem Kanalwechsel neu
+///
+/// Die Wahl des Clients bleibt erhalten, es gilt aber `b_voice_transmit` des
+/// neuen Kanals. Aendert sich der effektive Modus, erfahren der Client und
+/// die Kanalmitglieder per `ClientVoiceUpdated` davon.
+/// Ist der neue Kanal notfall-stumm geschaltet, erhaelt der Client
+/// ausserdem das `ChannelEmergencyMuteEvent`.
+pub(crate) async fn sendemodus_neu_bewerten<U, P, B>(
+    state: &SignalingState<U, P, B>,
+    user_id: UserId,
+    channel_id: ChannelId,
+) where
+    U: UserRepository + ServerGroupRepository + ChannelRepository + ChatMessageRepository + 'static,
+    P: PermissionRepository + 'static,
+    B: BanRepository + 'static,
+{
+    let vorher = state
+        .presence
+        .client_presence(&user_id)
+        .is_some_and(|p| p.nur_hoeren);
+    if sendemodus_anwenden(state, user_id, channel_id, None).await != vorher {
+        sendemodus_melden(state, user_id, channel_id);
+    }
+    crate::notfall::beim_beitritt(state, user_id, channel_id).await;
+}
+
+/// Meldet den Sendemodus eines Clients an den ganzen Kanal (inkl. ihm selbst)
+pub(crate) fn sendemodus_melden<U, P, B>(
+    state: &SignalingState<U, P, B>,
+    user_id: UserId,
+    channel_id: ChannelId,
+) where
+    U: UserRepository + ServerGroupRepository + ChannelRepository + ChatMessageRepository + 'static,
+    P: PermissionRepository + 'static,
+    B: BanRepository + 'static,
+{
+    state.broadcaster.an_channel_senden(
+        &channel_id,
+        ControlMessage::new(
+            0,
+            ControlPayload::ClientVoiceUpdated(ClientVoiceUpdatedEvent {
+                user_id,
+                channel_id,
+                ssrc: state.voice_state.ssrc_von_user(&user_id),
+                listen_only: state
+                    .presence
+                    .client_presence(&user_id)
+                    .is_some_and(|p| p.nur_hoeren),
+            }),
+        ),
+    );
+}
+
+/// Waehlt den Codec fuer eine Voice-Verbindung
+///
+/// Opus ist Standard. PCMU wird nur akzeptiert, wenn die Server-Richtlinie
+/// den Fallback fuer Clients ohne funktionierendes Opus erlaubt.
+fn codec_aushandeln(bevorzugt: &str, pcm_fallback_erlaubt: bool) -> AudioCodec {
+    match AudioCodec::aus_name(bevorzugt) {
+        Some(AudioCodec::Pcmu) if pcm_fallback_erlaubt => AudioCodec::Pcmu,
+        _ => AudioCodec::Opus,
+    }
+}
+
+/// Begrenzt die gewuenschte Opus-Konfiguration auf die Server-Richtlinie
+///
+/// Ein nicht erlaubtes Preset (oder eine eigene Konfiguration bei
+/// eingeschraenkten Presets) wird durch das erlaubte Preset mit der
+/// hoechsten Bitrate bis zur gewuenschten ersetzt, mangels eines solchen
+/// durch das sparsamste. FEC und DTX bleiben aus, wenn der Client sie nicht
+/// anfragt. Die Bitrate wird auf Server-Maximum und Upload-Grenze des
+/// Clients gekappt.
+pub fn opus_aushandeln(
+    anfrage: &CodecNegotiationRequest,
+    richtlinie: &CodecRichtlinie,
+) -> CodecNegotiationResponse {
+    let mut gruende = Vec::new();
+    let mut config = anfrage.requested.clone();
+    let mut preset = anfrage.preset_hint;
+
+    let preset_erlaubt = match preset {
+        Some(p) => richtlinie.presets().any(|erlaubt| erlaubt == p),
+        None => !richtlinie.presets_eingeschraenkt(),
+    };
+    if !preset_erlaubt {
+        let gewuenscht = preset.map_or(config.bitrate_kbps, |p| p.config().bitrate_kbps);
+        let ersatz = richtlinie
+            .presets()
+            .find(|p| p.config().bitrate_kbps <= gewuenscht)
+            .or_else(|| richtlinie.presets().last())
+            .unwrap_or(AudioPreset::LowBandwidth);
+        gruende.push(format!(
+            "{} nicht erlaubt, verwende {}",
+            preset.map_or("Eigene Konfiguration", |p| p.bezeichnung()),
+            ersatz.bezeichnung()
+        ));
+        config = ersatz.config();
+        config.fec_enabled &= anfrage.requested.fec_enabled;
+        config.dtx_enabled &= anfrage.requested.dtx_enabled;
+        preset = Some(ersatz);
+    }
+
+    if !anfrage.supported_sample_rates.is_empty()
+        && !anfrage.supported_sample_rates.contains(&config.sample_rate)
+    {
+        config.sample_rate = anfrage.supported_sample_rates[0];
+        gruende.push(format!(
+            "Abtastrate auf {} Hz gesetzt",
+            config.sample_rate as u32
+        ));
+    }
+    config.complexity = config.complexity.min(10);
+
+    let mut grenze = richtlinie.max_bitrate_kbps.min(510);
+    if anfrage.max_upload_kbps > 0 {
+        grenze = grenze.min(anfrage.max_upload_kbps);
+    }
+    let grenze = grenze.max(6);
+    if config.bitrate_kbps > grenze {
+        gruende.push(format!(
+            "Bitrate von {} auf {} kbps begrenzt",
+            config.bitrate_kbps, grenze
+        ));
+        config.bitrate_kbps = grenze;
+    }
+    config.bitrate_kbps = config.bitrate_kbps.max(6);
+
+    CodecNegotiationResponse {
+        status: if gruende.is_empty() {
+            NegotiationStatus::Accepted
+        } else {
+            NegotiationStatus::Adjusted
+        },
+        accepted: config,
+        adjustment_reason: (!gruende.is_empty()).then(|| gruende.join("; ")),
+        server_max_bitrate_kbps: richtlinie.max_bitrate_kbps,
+        preset,
+    }
+}
+
+/// Waehlt den Umgang mit serverseitig verworfenen Paketen
+///
+/// Umschreiben nur, wenn der Client es anbietet und die Header nicht Teil
+/// einer Ende-zu-Ende-Authentisierung sind; sonst werden sie gezaehlt.
+fn resequenzierung_aushandeln(angeboten: bool, crypto_mode: &str) -> Resequenzierung {
+    if angeboten && crypto_mode.parse::<CryptoMode>() != Ok(CryptoMode::E2E) {
+        Resequenzierung::Umschreiben
+    } else {
+        Resequenzierung::Melden
+    }
+}
+
 /// Verarbeitet VoiceInit-Anfrage (UDP Port Negotiation)
 ///
-/// Der Client teilt seinen UDP-Port und bevorzugten Codec mit.
-/// Der Server antwortet mit seiner UDP-Adresse und einer SSRC.
+/// Der Client teilt seinen bevorzugten Codec mit. Der Server antwortet mit
+/// seiner UDP-Adresse, einer SSRC und einer Nonce fuer das Hello; erst ein
+/// damit authentifiziertes Hello legt den UDP-Endpunkt des Clients fest
+/// (hinter NAT weicht er von allem ab, was hier bekannt ist).
+/// Gebannte Benutzer oder IPs erhalten keine Sitzung; eine bestehende wird
+/// abgebaut, sodass der UDP-Server ihre Pakete verwirft.
 pub async fn handle_voice_init<U, P, B>(
     request: VoiceInitRequest,
     request_id: u32,
@@ -43,36 +346,110 @@ where
     P: PermissionRepository + 'static,
     B: BanRepository + 'static,
 {
-    // Codec aushandeln (aktuell nur Opus unterstuetzt)
-    let akzeptierter_codec = if request.preferred_codec.to_lowercase() == "opus" {
-        "opus".to_string()
-    } else {
+    let peer_ip = peer_addr.ip().to_string();
+    match state
+        .ban_service
+        .aktiver_ban(Some(user_id.inner()), Some(&peer_ip))
+        .await
+    {
+        Ok(Some(ban)) => {
+            voice_abbauen(state, user_id, "Gebannt");
+            tracing::warn!(user_id = %user_id, ban_id = %ban.id, "Voice-Init gebannt abgelehnt");
+            return ban_abgelehnt(request_id, &ban);
+        }
+        Ok(None) => {}
+        Err(e) => tracing::error!("Ban-Pruefung fehlgeschlagen: {}", e),
+    }
+
+    if let Some(public_key) = &request.e2e_public_key {
+        if speakeasy_crypto::decode_public_key(public_key).is_err() {
+            return ControlMessage::error(
+                request_id,
+                ErrorCode::InvalidRequest,
+                "Ungueltiger E2E-Schluessel (erwartet: 32 Bytes X25519, Base64)",
+            );
+        }
+    }
+
+    let akzeptierter_codec =
+        codec_aushandeln(&request.preferred_codec, state.config.pcm_fallback_erlaubt);
+    if akzeptierter_codec.name() != request.preferred_codec.to_lowercase() {
         tracing::warn!(
             user_id = %user_id,
             codec = %request.preferred_codec,
-            "Unbekannter Codec angefordert, fallback auf opus"
+            "Codec nicht verfuegbar oder nicht erlaubt, fallback auf opus"
         );
-        "opus".to_string()
-    };
+    }
 
-    // UDP-Endpunkt des Clients aus der TCP-Verbindung + Client-Port ableiten
+    let opus = request
+        .codec_negotiation
+        .as_ref()
+        .filter(|_| akzeptierter_codec == AudioCodec::Opus)
+        .map(|anfrage| opus_aushandeln(anfrage, &state.config.codec));
+    if let Some(grund) = opus.as_ref().and_then(|o| o.adjustment_reason.as_deref()) {
+        tracing::info!(user_id = %user_id, grund, "Opus-Konfiguration herabgestuft");
+    }
+
+    // Nur vorlaeufig: den gueltigen UDP-Endpunkt bestaetigt das Hello
     let client_udp_addr = SocketAddr::new(peer_addr.ip(), request.client_udp_port);
+    let nonce = hello_nonce();
+    let hello_nonce = hello_nonce_kodieren(&nonce);
 
-    // SSRC zuweisen
-    let ssrc = naechste_ssrc();
+    if request.force_new {
+        voice_abbauen(state, user_id, "Neue Voice-Sitzung angefordert");
+    }
 
-    // Client im VoiceState registrieren (ohne Channel – Channel-Zuweisung erfolgt bei Join)
-    state
+    // Wiederholter VoiceInit (z.B. nach verlorener Antwort): bestehende SSRC
+    let ssrc = match state
         .voice_state
-        .client_registrieren(user_id, ssrc, client_udp_addr);
+        .sitzung_fortsetzen(&user_id, nonce.clone())
+    {
+        Some(ssrc) => {
+            tracing::info!(
+                user_id = %user_id,
+                ssrc,
+                client_udp = %client_udp_addr,
+                "Wiederholter Voice-Init, bestehende Sitzung bestaetigt"
+            );
+            ssrc
+        }
+        None => {
+            // SSRC zuweisen (eindeutig ueber alle Clients)
+            let ssrc = freie_ssrc(&state.voice_state);
 
-    tracing::info!(
-        user_id = %user_id,
-        ssrc,
-        client_udp = %client_udp_addr,
-        codec = %akzeptierter_codec,
-        "Voice-Init erfolgreich"
-    );
+            // Client im VoiceState registrieren (ohne Channel – Channel-Zuweisung erfolgt bei Join)
+            state
+                .voice_state
+                .client_anmelden(user_id, ssrc, client_udp_addr, nonce);
+            if state
+                .presence
+                .client_presence(&user_id)
+                .is_some_and(|p| p.nur_hoeren)
+            {
+                state.voice_state.senden_erlauben(&user_id, false);
+            }
+
+            // Kanalmitglieder erfahren die neue SSRC erst nach der Registrierung
+            if let Some(channel_id) = state.presence.channel_von_client(&user_id) {
+                ssrc_melden(state, user_id, channel_id, Some(ssrc));
+            }
+
+            tracing::info!(
+                user_id = %user_id,
+                ssrc,
+                client_udp = %client_udp_addr,
+                codec = akzeptierter_codec.name(),
+                "Voice-Init erfolgreich"
+            );
+            ssrc
+        }
+    };
+
+    // Takt der Frische-Pruefung folgt der vereinbarten Frame-Groesse
+    let codec_config = opus.as_ref().map(|o| o.accepted.clone());
+    state
+        .voice_state
+        .client_aktualisieren(&user_id, |s| s.codec_config = codec_config);
 
     // Krypto-Modus und DTLS-Fingerprint aus Server-Konfiguration laden
     let crypto_mode = state.config.crypto_mode.clone();
@@ -86,19 +463,74 @@ where
         );
     }
 
+    let resequenzierung = resequenzierung_aushandeln(request.resequencing, &crypto_mode);
+    state
+        .channel_router
+        .resequenzierung_setzen(user_id, resequenzierung);
+
+    // Neuer oder geaenderter Schluessel: der Kanal braucht eine neue Epoch,
+    // die den Client einschliesst
+    if let Some(public_key) = request.e2e_public_key.filter(|_| e2e_aktiv(state)) {
+        if state.e2e.schluessel_setzen(user_id, public_key) {
+            if let Some(channel_id) = state.presence.channel_von_client(&user_id) {
+                rotation_anstossen(state, channel_id, KeyRotationReason::MemberJoined);
+            }
+        }
+    }
+
     ControlMessage::new(
         request_id,
         ControlPayload::VoiceReady(VoiceReadyResponse {
             server_udp_port: state.config.voice_udp_port,
             server_ip: state.config.voice_server_ip.clone(),
             ssrc,
-            codec: akzeptierter_codec,
+            codec: akzeptierter_codec.name().to_string(),
+            codec_negotiation: opus,
             server_dtls_fingerprint,
             crypto_mode,
+            resequencing: resequenzierung == Resequenzierung::Umschreiben,
+            hello_nonce: Some(hello_nonce),
         }),
     )
 }
 
+/// Baut die Voice-Sitzung eines Clients ab (VoiceTeardown)
+///
+/// Entfernt ihn aus Voice-State und Channel-Router und meldet den
+/// Kanalmitgliedern, dass seine SSRC nicht mehr gilt. Ein gemeldeter
+/// E2E-Schluessel verfaellt; der Kanal rotiert ohne ihn. Gibt `true`
+/// zurueck, wenn eine Registrierung bestand.
+fn voice_abbauen<U, P, B>(state: &SignalingState<U, P, B>, user_id: UserId, grund: &str) -> bool
+where
+    U: UserRepository + ServerGroupRepository + ChannelRepository + ChatMessageRepository + 'static,
+    P: PermissionRepository + 'static,
+    B: BanRepository + 'static,
+{
+    let registriert = state.voice_state.client_entfernen(&user_id).is_some();
+    if registriert {
+        tracing::info!(
+            user_id = %user_id,
+            grund = %grund,
+            "Voice-Verbindung getrennt"
+        );
+    }
+
+    // Aus Channel-Router entfernen
+    state.channel_router.kanal_verlassen(&user_id);
+    state
+        .channel_router
+        .resequenzierung_setzen(user_id, Resequenzierung::Aus);
+
+    let schluessel_entfernt = state.e2e.schluessel_entfernen(&user_id);
+    if let Some(channel_id) = state.presence.channel_von_client(&user_id) {
+        ssrc_melden(state, user_id, channel_id, None);
+        if schluessel_entfernt {
+            rotation_anstossen(state, channel_id, KeyRotationReason::MemberLeft);
+        }
+    }
+    registriert
+}
+
 /// Verarbeitet VoiceDisconnect-Anfrage
 ///
 /// Entfernt den Client aus dem Voice-State und dem Channel-Router.
@@ -114,21 +546,10 @@ where
     B: BanRepository + 'static,
 {
     let grund = request.reason.as_deref().unwrap_or("Kein Grund");
-
-    // Aus Voice-State entfernen
-    if state.voice_state.client_entfernen(&user_id).is_some() {
-        tracing::info!(
-            user_id = %user_id,
-            grund = %grund,
-            "Voice-Verbindung getrennt"
-        );
-    } else {
+    if !voice_abbauen(state, user_id, grund) {
         tracing::debug!(user_id = %user_id, "Voice-Disconnect fuer nicht-registrierten Client");
     }
 
-    // Aus Channel-Router entfernen
-    state.channel_router.kanal_verlassen(&user_id);
-
     // Bestaetigung mit leerer Pong-Nachricht
     ControlMessage::new(
         request_id,
@@ -137,12 +558,154 @@ where
             server_ip: String::new(),
             ssrc: 0,
             codec: String::new(),
+            codec_negotiation: None,
             server_dtls_fingerprint: None,
             crypto_mode: "none".to_string(),
+            resequencing: false,
+            hello_nonce: None,
         }),
     )
 }
 
+/// Verarbeitet einen VoiceStats-Bericht
+///
+/// Uebernimmt die vom Client gemeldete Downlink-Verlustrate und antwortet
+/// mit der Empfangsstatistik des Servers fuer den Stream des Clients
+/// (Uplink) sowie den Benutzern hinter den gemeldeten SSRCs. Ohne
+/// Resequenzierung zaehlen serverseitig verworfene Pakete nicht als
+/// Downlink-Verlust und werden dem Client mitgeteilt.
+///
+/// Jeder Bericht ist zugleich ein Messintervall der Bitrate-Regelung.
+/// Aendert sich deren Empfehlung, beginnt die Folge mit einem
+/// `VoiceQualityUpdate`; die Antwort steht immer am Schluss.
+///
+/// Die Sprachqualitaet (MOS) der Sitzung uebernimmt der Server vom Client;
+/// aeltere Clients melden keine, dann schaetzt er sie selbst.
+pub async fn handle_voice_stats<U, P, B>(
+    request: VoiceStatsReport,
+    request_id: u32,
+    user_id: UserId,
+    state: &Arc<SignalingState<U, P, B>>,
+) -> Vec<ControlMessage>
+where
+    U: UserRepository + ServerGroupRepository + ChannelRepository + ChatMessageRepository + 'static,
+    P: PermissionRepository + 'static,
+    B: BanRepository + 'static,
+{
+    let suppressed: Vec<SsrcSuppressed> = state
+        .channel_router
+        .unterdrueckt_fuer(&user_id)
+        .into_iter()
+        .map(|(ssrc, suppressed)| SsrcSuppressed { ssrc, suppressed })
+        .collect();
+    let (empfangen, erwartet) = request.downlink.iter().fold((0u64, 0u64), |(e, x), s| {
+        let verworfen = suppressed
+            .iter()
+            .find(|v| v.ssrc == s.ssrc)
+            .map_or(0, |v| v.suppressed);
+        (e + s.received, x + s.expected.saturating_sub(verworfen))
+    });
+
+    let mut uplink = None;
+    let mut anpassung = None;
+    state.voice_state.client_aktualisieren(&user_id, |s| {
+        s.downlink_verlust_rate = verlust_rate(empfangen, erwartet);
+        s.verlust_rate = s.uplink.verlust_rate();
+        if let Some(rtt_ms) = request.rtt_ms {
+            s.rtt_ms = rtt_ms;
+        }
+        anpassung = s
+            .bitrate
+            .bericht(s.uplink.empfangen(), s.uplink.erwartet(), request.rtt_ms);
+        s.mos = Some(match request.mos {
+            Some(mos) => f64::from(mos),
+            None => s.mos_schaetzen(),
+        });
+        uplink = Some(SsrcReceiveStats {
+            ssrc: s.ssrc,
+            received: s.uplink.empfangen(),
+            expected: s.uplink.erwartet(),
+        });
+    });
+
+    let Some(uplink) = uplink else {
+        return vec![ControlMessage::error(
+            request_id,
+            ErrorCode::InvalidRequest,
+            "Keine aktive Voice-Verbindung",
+        )];
+    };
+
+    let senders = request
+        .downlink
+        .iter()
+        .filter_map(|s| {
+            state
+                .voice_state
+                .user_id_von_ssrc(s.ssrc)
+                .map(|uid| SsrcSender {
+                    ssrc: s.ssrc,
+                    user_id: uid,
+                })
+        })
+        .collect();
+
+    tracing::debug!(
+        user_id = %user_id,
+        uplink_empfangen = uplink.received,
+        uplink_erwartet = uplink.expected,
+        downlink_empfangen = empfangen,
+        downlink_erwartet = erwartet,
+        wiedergabe_ueberlauf = request
+            .playback_overflow
+            .iter()
+            .map(|o| o.overflow)
+            .sum::<u64>(),
+        "Voice-Statistik ausgetauscht"
+    );
+
+    let mut folge: Vec<ControlMessage> = anpassung
+        .and_then(|a| qualitaet_melden(request_id, user_id, a))
+        .into_iter()
+        .collect();
+    folge.push(ControlMessage::new(
+        request_id,
+        ControlPayload::VoiceStatsResponse(VoiceStatsResponse {
+            uplink,
+            senders,
+            suppressed,
+        }),
+    ));
+    folge
+}
+
+/// `VoiceQualityUpdate` zu einer geaenderten Bitrate-Empfehlung
+fn qualitaet_melden(
+    request_id: u32,
+    user_id: UserId,
+    anpassung: BitrateAnpassung,
+) -> Option<ControlMessage> {
+    let reason = match anpassung.aktion {
+        CongestionAktion::BitrateReduzieren { .. } => VoiceQualityReason::PacketLoss,
+        CongestionAktion::Kritisch { .. } => VoiceQualityReason::Critical,
+        CongestionAktion::BitrateErhoehen { .. } => VoiceQualityReason::Recovery,
+        CongestionAktion::Stabil | CongestionAktion::RttWarnung { .. } => return None,
+    };
+    tracing::info!(
+        user_id = %user_id,
+        bitrate_kbps = anpassung.bitrate_kbps,
+        grund = ?reason,
+        "Neue Ziel-Bitrate fuer Voice-Client"
+    );
+    Some(ControlMessage::new(
+        request_id,
+        ControlPayload::VoiceQualityUpdate(VoiceQualityUpdate {
+            target_bitrate_kbps: anpassung.bitrate_kbps,
+            reason,
+        }),
+    ))
+}
+
 // ---------------------------------------------------------------------------
 // Tests
 // ---------------------------------------------------------------------------
@@ -150,6 +713,483 @@ where
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::handlers::client_handler::client_verschieben;
+    use crate::server_state::SignalingConfig;
+    use crate::test_hilfen::{neuer_client, TestAufbau, TestState};
+    use speakeasy_protocol::codec::SampleRate;
+    use speakeasy_protocol::ssrc::SsrcZuordnung;
+    use tokio::sync::mpsc;
+
+    async fn state() -> TestState {
+        state_mit(SignalingConfig::default()).await
+    }
+
+    async fn state_mit(config: SignalingConfig) -> TestState {
+        TestAufbau::default().config(config).bauen().await
+    }
+
+    fn verbinden(state: &TestState) -> (UserId, mpsc::Receiver<ControlMessage>) {
+        let user_id = neuer_client(state, None);
+        (user_id, state.broadcaster.client_registrieren(user_id))
+    }
+
+    async fn voice_init(state: &TestState, user_id: UserId, force_new: bool) -> u32 {
+        let request = VoiceInitRequest {
+            client_udp_port: 40000,
+            preferred_codec: "opus".into(),
+            codec_negotiation: None,
+            dtls_fingerprint: None,
+            force_new,
+            resequencing: true,
+            e2e_public_key: None,
+        };
+        let peer = "127.0.0.1:50000".parse().unwrap();
+        match handle_voice_init(request, 1, user_id, peer, state)
+            .await
+            .payload
+        {
+            ControlPayload::VoiceReady(antwort) => antwort.ssrc,
+            andere => panic!("VoiceReady erwartet: {andere:?}"),
+        }
+    }
+
+    /// Fuettert die Client-Zuordnung mit allen bisher empfangenen Nachrichten
+    fn empfangen(rx: &mut mpsc::Receiver<ControlMessage>, zuordnung: &mut SsrcZuordnung) {
+        while let Ok(nachricht) = rx.try_recv() {
+            zuordnung.anwenden(&nachricht.payload);
+        }
+    }
+
+    #[tokio::test]
+    async fn client_zuordnung_folgt_ssrc_wechsel() {
+        let state = state().await;
+        let kanal = ChannelId::new();
+        let (a, _rx_a) = verbinden(&state);
+        let (b, mut rx_b) = verbinden(&state);
+        let mut zuordnung_b = SsrcZuordnung::neu();
+
+        client_verschieben(&state, a, kanal, None);
+        let erste = voice_init(&state, a, false).await;
+        client_verschieben(&state, b, kanal, None);
+        empfangen(&mut rx_b, &mut zuordnung_b);
+        assert_eq!(zuordnung_b.user_von_ssrc(erste), Some(a));
+
+        // Neuverhandlung mitten in der Sitzung
+        let zweite = voice_init(&state, a, true).await;
+        assert_ne!(erste, zweite);
+        assert!(!state.voice_state.ssrc_belegt(erste));
+        empfangen(&mut rx_b, &mut zuordnung_b);
+        assert_eq!(zuordnung_b.user_von_ssrc(zweite), Some(a));
+        assert_eq!(zuordnung_b.user_von_ssrc(erste), None);
+        assert_eq!(zuordnung_b.len(), 1);
+
+        let _ =
+            handle_voice_disconnect(VoiceDisconnectRequest { reason: None }, 2, a, &state).await;
+        empfangen(&mut rx_b, &mut zuordnung_b);
+        assert!(zuordnung_b.is_empty());
+    }
+
+    #[tokio::test]
+    async fn ip_ban_baut_voice_sitzung_ab() {
+        let state = state().await;
+        let (a, _rx_a) = verbinden(&state);
+        let ssrc = voice_init(&state, a, false).await;
+        state
+            .ban_service
+            .ip_bannen(None, "127.0.0.1", "Stoerer", None)
+            .await
+            .unwrap();
+
+        let request = VoiceInitRequest {
+            client_udp_port: 40000,
+            preferred_codec: "opus".into(),
+            codec_negotiation: None,
+            dtls_fingerprint: None,
+            force_new: false,
+            resequencing: true,
+            e2e_public_key: None,
+        };
+        let peer = "127.0.0.1:50000".parse().unwrap();
+        match handle_voice_init(request, 2, a, peer, &state).await.payload {
+            ControlPayload::Error(fehler) => {
+                assert_eq!(fehler.code, ErrorCode::Banned);
+                assert_eq!(fehler.details.unwrap()["reason"], "Stoerer");
+            }
+            andere => panic!("Error erwartet: {andere:?}"),
+        }
+        // Der UDP-Server ordnet Pakete von SSRC und Endpunkt niemandem mehr zu
+        assert_eq!(state.voice_state.user_id_von_ssrc(ssrc), None);
+        let endpunkt = "127.0.0.1:40000".parse().unwrap();
+        assert_eq!(state.voice_state.user_id_von_endpunkt(&endpunkt), None);
+    }
+
+    #[tokio::test]
+    async fn verlorene_voice_ready_antwort_vergibt_keine_zweite_ssrc() {
+        let state = state().await;
+        let kanal = ChannelId::new();
+        let (a, _rx_a) = verbinden(&state);
+        let (b, mut rx_b) = verbinden(&state);
+        let mut zuordnung_b = SsrcZuordnung::neu();
+        client_verschieben(&state, b, kanal, None);
+        client_verschieben(&state, a, kanal, None);
+
+        // Erste Antwort geht beim Client verloren, er wiederholt die Anfrage
+        let _verloren = voice_init(&state, a, false).await;
+        let ssrc = voice_init(&state, a, false).await;
+        let wiederholt = voice_init(&state, a, false).await;
+
+        assert_eq!(ssrc, wiederholt);
+        assert_eq!(state.voice_state.client_anzahl(), 1);
+        assert_eq!(state.voice_state.ssrc_von_user(&a), Some(ssrc));
+        assert_eq!(state.voice_state.user_id_von_ssrc(ssrc), Some(a));
+        empfangen(&mut rx_b, &mut zuordnung_b);
+        assert_eq!(zuordnung_b.user_von_ssrc(ssrc), Some(a));
+        assert_eq!(zuordnung_b.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn voice_ready_nonce_authentifiziert_hello() {
+        use speakeasy_protocol::voice::{hello_nonce_dekodieren, HelloPaket};
+        use speakeasy_voice::HelloErgebnis;
+
+        let state = state().await;
+        let (a, _rx_a) = verbinden(&state);
+        let request = VoiceInitRequest {
+            client_udp_port: 0,
+            preferred_codec: "opus".into(),
+            codec_negotiation: None,
+            dtls_fingerprint: None,
+            force_new: false,
+            resequencing: true,
+            e2e_public_key: None,
+        };
+        let peer = "127.0.0.1:50000".parse().unwrap();
+        let antwort = match handle_voice_init(request, 1, a, peer, &state).await.payload {
+            ControlPayload::VoiceReady(antwort) => antwort,
+            andere => panic!("VoiceReady erwartet: {andere:?}"),
+        };
+        let nonce = hello_nonce_dekodieren(&antwort.hello_nonce.unwrap()).unwrap();
+        assert_eq!(nonce.len(), HELLO_NONCE_LAENGE);
+
+        // Ohne Hello kennt der UDP-Server keinen Endpunkt des Clients
+        let vorlaeufig = "127.0.0.1:0".parse().unwrap();
+        assert_eq!(state.voice_state.user_id_von_endpunkt(&vorlaeufig), None);
+
+        // Beobachtete Adresse hinter NAT
+        let beobachtet = "203.0.113.7:61000".parse().unwrap();
+        let falsch = HelloPaket::neu(antwort.ssrc, 0, &[0u8; HELLO_NONCE_LAENGE]);
+        assert_eq!(
+            state.voice_state.hello_pruefen(&falsch, beobachtet),
+            HelloErgebnis::Ungueltig
+        );
+        let hello = HelloPaket::neu(antwort.ssrc, 1, &nonce);
+        assert_eq!(
+            state.voice_state.hello_pruefen(&hello, beobachtet),
+            HelloErgebnis::Bestaetigt(a)
+        );
+        assert_eq!(state.voice_state.user_id_von_endpunkt(&beobachtet), Some(a));
+    }
+
+    #[tokio::test]
+    async fn kanalwechsel_entfernt_zuordnung() {
+        let state = state().await;
+        let kanal = ChannelId::new();
+        let (a, _rx_a) = verbinden(&state);
+        let (b, mut rx_b) = verbinden(&state);
+        let mut zuordnung_b = SsrcZuordnung::neu();
+
+        client_verschieben(&state, b, kanal, None);
+        client_verschieben(&state, a, kanal, None);
+        let ssrc = voice_init(&state, a, false).await;
+        empfangen(&mut rx_b, &mut zuordnung_b);
+        assert_eq!(zuordnung_b.user_von_ssrc(ssrc), Some(a));
+
+        client_verschieben(&state, a, ChannelId::new(), None);
+        empfangen(&mut rx_b, &mut zuordnung_b);
+        assert!(zuordnung_b.is_empty());
+    }
+
+    #[test]
+    fn pcmu_nur_mit_erlaubtem_fallback() {
+        assert_eq!(codec_aushandeln("opus", true), AudioCodec::Opus);
+        assert_eq!(codec_aushandeln("PCMU", true), AudioCodec::Pcmu);
+        assert_eq!(codec_aushandeln("pcmu", false), AudioCodec::Opus);
+        assert_eq!(codec_aushandeln("speex", true), AudioCodec::Opus);
+    }
+
+    #[tokio::test]
+    async fn voice_ready_ohne_fallback_bleibt_opus() {
+        let state = state().await;
+        let (a, _rx_a) = verbinden(&state);
+        let request = VoiceInitRequest {
+            client_udp_port: 40000,
+            preferred_codec: "pcmu".into(),
+            codec_negotiation: None,
+            dtls_fingerprint: None,
+            force_new: false,
+            resequencing: false,
+            e2e_public_key: None,
+        };
+        let peer = "127.0.0.1:50000".parse().unwrap();
+        // Standard-Richtlinie: kein PCMU-Fallback
+        match handle_voice_init(request, 1, a, peer, &state).await.payload {
+            ControlPayload::VoiceReady(antwort) => assert_eq!(antwort.codec, "opus"),
+            andere => panic!("VoiceReady erwartet: {andere:?}"),
+        }
+    }
+
+    fn richtlinie(max_bitrate_kbps: u16, erlaubte_presets: &[AudioPreset]) -> CodecRichtlinie {
+        CodecRichtlinie {
+            max_bitrate_kbps,
+            erlaubte_presets: erlaubte_presets.to_vec(),
+        }
+    }
+
+    #[test]
+    fn opus_innerhalb_der_richtlinie_bleibt_unveraendert() {
+        let anfrage = CodecNegotiationRequest::fuer_preset(AudioPreset::Balanced);
+        let antwort = opus_aushandeln(&anfrage, &CodecRichtlinie::default());
+        assert_eq!(antwort.status, NegotiationStatus::Accepted);
+        assert_eq!(antwort.accepted, AudioPreset::Balanced.config());
+        assert_eq!(antwort.preset, Some(AudioPreset::Balanced));
+        assert_eq!(antwort.adjustment_reason, None);
+    }
+
+    #[test]
+    fn opus_wird_herabgestuft_statt_abgelehnt() {
+        let richtlinie = richtlinie(48, &[AudioPreset::Speech, AudioPreset::Balanced]);
+
+        // Musik ist nicht erlaubt: Ausgewogen, auf das Server-Maximum gekappt
+        let musik = CodecNegotiationRequest::fuer_preset(AudioPreset::Music);
+        let antwort = opus_aushandeln(&musik, &richtlinie);
+        assert_eq!(antwort.status, NegotiationStatus::Adjusted);
+        assert_eq!(antwort.preset, Some(AudioPreset::Balanced));
+        assert_eq!(antwort.accepted.bitrate_kbps, 48);
+        assert_eq!(
+            antwort.accepted.channels,
+            AudioPreset::Balanced.config().channels
+        );
+        // Musik fragt kein FEC an, also bleibt es aus
+        assert!(!antwort.accepted.fec_enabled);
+        assert_eq!(antwort.server_max_bitrate_kbps, 48);
+        assert!(antwort.adjustment_reason.unwrap().contains("Musik"));
+
+        // Upload-Grenze des Clients liegt unter dem Server-Maximum
+        let mut knapp = CodecNegotiationRequest::fuer_preset(AudioPreset::Speech);
+        knapp.max_upload_kbps = 24;
+        let antwort = opus_aushandeln(&knapp, &richtlinie);
+        assert_eq!(antwort.preset, Some(AudioPreset::Speech));
+        assert_eq!(antwort.accepted.bitrate_kbps, 24);
+
+        // Eigene Konfiguration bei eingeschraenkten Presets
+        let mut eigene = CodecNegotiationRequest::fuer_preset(AudioPreset::Music);
+        eigene.preset_hint = None;
+        eigene.requested.bitrate_kbps = 40;
+        let antwort = opus_aushandeln(&eigene, &richtlinie);
+        assert_eq!(antwort.preset, Some(AudioPreset::Speech));
+        assert_eq!(antwort.accepted.bitrate_kbps, 32);
+    }
+
+    #[test]
+    fn opus_ohne_passendes_preset_nimmt_das_sparsamste() {
+        let richtlinie = richtlinie(510, &[AudioPreset::Music, AudioPreset::Balanced]);
+        let mut anfrage = CodecNegotiationRequest::fuer_preset(AudioPreset::LowBandwidth);
+        anfrage.supported_sample_rates = vec![SampleRate::Hz16000];
+
+        let antwort = opus_aushandeln(&anfrage, &richtlinie);
+        assert_eq!(antwort.preset, Some(AudioPreset::Balanced));
+        assert_eq!(antwort.accepted.bitrate_kbps, 64);
+        assert_eq!(antwort.accepted.sample_rate, SampleRate::Hz16000);
+        // Niedrige Bandbreite fragt kein FEC an
+        assert!(!antwort.accepted.fec_enabled);
+    }
+
+    #[tokio::test]
+    async fn voice_init_liefert_vereinbarte_opus_konfiguration() {
+        let state = state_mit(SignalingConfig {
+            codec: richtlinie(48, &[AudioPreset::Speech, AudioPreset::Balanced]),
+            ..SignalingConfig::default()
+        })
+        .await;
+        let (a, _rx_a) = verbinden(&state);
+        let request = VoiceInitRequest {
+            client_udp_port: 40000,
+            preferred_codec: "opus".into(),
+            codec_negotiation: Some(CodecNegotiationRequest::fuer_preset(AudioPreset::Music)),
+            dtls_fingerprint: None,
+            force_new: false,
+            resequencing: true,
+            e2e_public_key: None,
+        };
+        let peer = "127.0.0.1:50000".parse().unwrap();
+        let antwort = match handle_voice_init(request, 1, a, peer, &state).await.payload {
+            ControlPayload::VoiceReady(antwort) => antwort,
+            andere => panic!("VoiceReady erwartet: {andere:?}"),
+        };
+        let opus = antwort.codec_negotiation.unwrap();
+        assert_eq!(opus.status, NegotiationStatus::Adjusted);
+        assert_eq!(opus.preset, Some(AudioPreset::Balanced));
+        assert_eq!(opus.accepted.bitrate_kbps, 48);
+        assert_eq!(
+            state.voice_state.client_state(&a).unwrap().codec_config,
+            Some(opus.accepted)
+        );
+
+        // Ohne Anfrage bleibt es beim Server-Standard
+        let b = verbinden(&state).0;
+        let _ = voice_init(&state, b, false).await;
+        assert_eq!(
+            state.voice_state.client_state(&b).unwrap().codec_config,
+            None
+        );
+    }
+
+    #[test]
+    fn resequenzierung_nur_ohne_e2e() {
+        assert_eq!(
+            resequenzierung_aushandeln(true, "dtls"),
+            Resequenzierung::Umschreiben
+        );
+        assert_eq!(
+            resequenzierung_aushandeln(true, "e2e"),
+            Resequenzierung::Melden
+        );
+        assert_eq!(
+            resequenzierung_aushandeln(false, "none"),
+            Resequenzierung::Melden
+        );
+    }
+
+    #[tokio::test]
+    async fn stats_rechnen_serverseitig_verworfene_pakete_heraus() {
+        let state = state().await;
+        let kanal = ChannelId::new();
+        let (sprecher, _rx_s) = verbinden(&state);
+        let (hoerer, _rx_h) = verbinden(&state);
+        let ssrc = voice_init(&state, sprecher, false).await;
+
+        // Hoerer ohne Resequenzierung (z.B. E2E-Client)
+        let request = VoiceInitRequest {
+            client_udp_port: 40001,
+            preferred_codec: "opus".into(),
+            codec_negotiation: None,
+            dtls_fingerprint: None,
+            force_new: false,
+            resequencing: false,
+            e2e_public_key: None,
+        };
+        let peer = "127.0.0.1:50001".parse().unwrap();
+        match handle_voice_init(request, 1, hoerer, peer, &state)
+            .await
+            .payload
+        {
+            ControlPayload::VoiceReady(antwort) => assert!(!antwort.resequencing),
+            andere => panic!("VoiceReady erwartet: {andere:?}"),
+        }
+
+        let _rx1 = state.channel_router.kanal_beitreten(
+            sprecher,
+            kanal,
+            "127.0.0.1:40000".parse().unwrap(),
+        );
+        let _rx2 =
+            state
+                .channel_router
+                .kanal_beitreten(hoerer, kanal, "127.0.0.1:40001".parse().unwrap());
+        for seq in 1..=5 {
+            let paket = speakeasy_protocol::voice::VoicePacket::neu_silence(seq, seq * 960, ssrc);
+            state
+                .channel_router
+                .paket_unterdrueckt(&paket.header, &sprecher);
+        }
+
+        let bericht = VoiceStatsReport {
+            highest_sequence_sent: None,
+            downlink: vec![SsrcReceiveStats {
+                ssrc,
+                received: 95,
+                expected: 100,
+            }],
+            rtt_ms: None,
+            mos: None,
+            playback_overflow: Vec::new(),
+        };
+        let mut folge = handle_voice_stats(bericht, 2, hoerer, &state).await;
+        assert_eq!(folge.len(), 1);
+        match folge.remove(0).payload {
+            ControlPayload::VoiceStatsResponse(antwort) => {
+                assert_eq!(antwort.suppressed.len(), 1);
+                assert_eq!(antwort.suppressed[0].ssrc, ssrc);
+                assert_eq!(antwort.suppressed[0].suppressed, 5);
+            }
+            andere => panic!("VoiceStatsResponse erwartet: {andere:?}"),
+        }
+        let hoerer_state = state.voice_state.client_state(&hoerer).unwrap();
+        assert_eq!(hoerer_state.downlink_verlust_rate, 0.0);
+    }
+
+    #[tokio::test]
+    async fn kritischer_bericht_senkt_ziel_bitrate() {
+        let state = state().await;
+        let (a, _rx_a) = verbinden(&state);
+        voice_init(&state, a, false).await;
+
+        // Jedes fuenfte Paket fehlt beim Server (20 % Uplink-Verlust)
+        state.voice_state.client_aktualisieren(&a, |s| {
+            for seq in (0..100).filter(|seq| seq % 5 != 2) {
+                s.uplink.paket(seq);
+            }
+        });
+        let bericht = VoiceStatsReport {
+            highest_sequence_sent: Some(99),
+            downlink: Vec::new(),
+            rtt_ms: Some(250),
+            mos: None,
+            playback_overflow: Vec::new(),
+        };
+        let folge = handle_voice_stats(bericht, 7, a, &state).await;
+        assert_eq!(folge.len(), 2);
+        assert!(folge.iter().all(|n| n.request_id == 7));
+        match &folge[0].payload {
+            ControlPayload::VoiceQualityUpdate(update) => {
+                assert_eq!(update.reason, VoiceQualityReason::Critical);
+                // 64 * 0.75 * 0.75
+                assert_eq!(update.target_bitrate_kbps, 36);
+            }
+            andere => panic!("VoiceQualityUpdate erwartet: {andere:?}"),
+        }
+        assert!(matches!(
+            folge[1].payload,
+            ControlPayload::VoiceStatsResponse(_)
+        ));
+        {
+            let client = state.voice_state.client_state(&a).unwrap();
+            assert_eq!(client.rtt_ms, 250);
+            // Ohne Note des Clients schaetzt der Server (20 % Verlust)
+            assert!(client.mos.unwrap() < 3.0);
+        }
+
+        // Ohne neuen Verlust bleibt die Empfehlung und es kommt keine Meldung
+        let bericht = VoiceStatsReport {
+            highest_sequence_sent: Some(99),
+            downlink: Vec::new(),
+            rtt_ms: Some(250),
+            mos: Some(3.1),
+            playback_overflow: Vec::new(),
+        };
+        assert_eq!(handle_voice_stats(bericht, 8, a, &state).await.len(), 1);
+        // Die Note des Clients hat Vorrang
+        let mos = state.voice_state.client_state(&a).unwrap().mos.unwrap();
+        assert!((mos - 3.1).abs() < 1e-6);
+    }
+
+    #[test]
+    fn freie_ssrc_ueberspringt_belegte() {
+        let voice_state = VoiceState::neu();
+        let naechste = naechste_ssrc() + 1;
+        voice_state.client_registrieren(UserId::new(), naechste, "127.0.0.1:1".parse().unwrap());
+        assert_ne!(freie_ssrc(&voice_state), naechste);
+    }
 
     #[test]
     fn ssrc_monoton_steigend() {
@@ -0,0 +1,207 @@
+//! Kanalbaum fuer Kanallisten-Antworten
+//!
+//! Auf Servern mit sehr vielen Kanaelen wird der Baum nicht vollstaendig
+//! ausgeliefert. Oberhalb von [`SignalingConfig::kanalbaum_teilweise_ab`]
+//! enthaelt die erste Kanalliste nur die Root-Ebene und den Pfad zum Kanal
+//! des Clients; weitere Zweige laedt der Client per `ChannelTreeExpand` nach.
+//!
+//! Jeder ausgelieferte Kanal traegt `child_count` und `has_children`
+//! (Unterkanaele vorhanden, die nicht in der Antwort enthalten sind).
+//!
+//! [`SignalingConfig::kanalbaum_teilweise_ab`]: crate::server_state::SignalingConfig::kanalbaum_teilweise_ab
+
+use std::collections::{HashMap, HashSet};
+
+use speakeasy_core::types::ChannelId;
+use speakeasy_protocol::control::ChannelInfo;
+
+/// Standard-Schwelle, ab der nur noch Teilbaeume ausgeliefert werden
+pub const STANDARD_TEILWEISE_AB: usize = 500;
+
+/// Maximale Tiefe eines Teilbaums, sobald die Schwelle ueberschritten ist
+pub const MAX_TEILBAUM_TIEFE: u32 = 3;
+
+/// Alle Kanaele eines Servers, nach Eltern indiziert
+pub struct Kanalbaum {
+    kanaele: HashMap<ChannelId, ChannelInfo>,
+    kinder: HashMap<Option<ChannelId>, Vec<ChannelId>>,
+}
+
+impl Kanalbaum {
+    /// Baut den Baum auf; Kanaele mit unbekanntem Eltern haengen an der Root
+    pub fn neu(kanaele: Vec<ChannelInfo>) -> Self {
+        let kanaele: HashMap<ChannelId, ChannelInfo> =
+            kanaele.into_iter().map(|k| (k.channel_id, k)).collect();
+
+        let mut kinder: HashMap<Option<ChannelId>, Vec<ChannelId>> = HashMap::new();
+        for kanal in kanaele.values() {
+            let parent = eltern(&kanaele, kanal);
+            kinder.entry(parent).or_default().push(kanal.channel_id);
+        }
+        for ids in kinder.values_mut() {
+            ids.sort_by_key(|id| (kanaele[id].sort_order, id.inner()));
+        }
+
+        Self { kanaele, kinder }
+    }
+
+    /// Anzahl aller Kanaele
+    pub fn len(&self) -> usize {
+        self.kanaele.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.kanaele.is_empty()
+    }
+
+    pub fn enthaelt(&self, channel_id: &ChannelId) -> bool {
+        self.kanaele.contains_key(channel_id)
+    }
+
+    /// Der ganze Baum
+    pub fn vollstaendig(&self) -> Vec<ChannelInfo> {
+        self.ausliefern(self.kanaele.keys().copied().collect())
+    }
+
+    /// Root-Ebene plus Pfad zu `kanal` (Vorfahren und der Kanal selbst)
+    pub fn snapshot(&self, kanal: Option<ChannelId>) -> Vec<ChannelInfo> {
+        let mut auswahl: HashSet<ChannelId> = self.kinder_von(None).iter().copied().collect();
+        let mut pfad = HashSet::new();
+        let mut aktuell = kanal.filter(|id| self.enthaelt(id));
+        // `pfad` schuetzt vor Zyklen in fehlerhaften Daten
+        while let Some(id) = aktuell.filter(|id| pfad.insert(*id)) {
+            auswahl.insert(id);
+            aktuell = eltern(&self.kanaele, &self.kanaele[&id]);
+        }
+        self.ausliefern(auswahl)
+    }
+
+    /// Teilbaum unterhalb von `wurzel` mit `tiefe` Ebenen
+    ///
+    /// Ist `wurzel` gesetzt, ist der Kanal selbst enthalten. `None` liefert
+    /// ab der Root-Ebene.
+    pub fn teilbaum(&self, wurzel: Option<ChannelId>, tiefe: u32) -> Vec<ChannelInfo> {
+        let mut auswahl = HashSet::new();
+        if let Some(id) = wurzel {
+            if !self.enthaelt(&id) {
+                return Vec::new();
+            }
+            auswahl.insert(id);
+        }
+
+        let mut ebene = vec![wurzel];
+        for _ in 0..tiefe {
+            let naechste: Vec<Option<ChannelId>> = ebene
+                .iter()
+                .flat_map(|parent| self.kinder_von(*parent))
+                .map(|id| Some(*id))
+                .collect();
+            if naechste.is_empty() {
+                break;
+            }
+            auswahl.extend(naechste.iter().flatten());
+            ebene = naechste;
+        }
+        self.ausliefern(auswahl)
+    }
+
+    fn kinder_von(&self, parent: Option<ChannelId>) -> &[ChannelId] {
+        self.kinder
+            .get(&parent)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    /// Gibt die ausgewaehlten Kanaele in Baumreihenfolge zurueck und setzt
+    /// `child_count` und `has_children`
+    fn ausliefern(&self, auswahl: HashSet<ChannelId>) -> Vec<ChannelInfo> {
+        // Start bei allen ausgewaehlten Kanaelen, deren Eltern fehlen
+        let mut stapel: Vec<ChannelId> = auswahl
+            .iter()
+            .filter(|id| {
+                eltern(&self.kanaele, &self.kanaele[id]).is_none_or(|p| !auswahl.contains(&p))
+            })
+            .copied()
+            .collect();
+        stapel.sort_by_key(|id| std::cmp::Reverse((self.kanaele[id].sort_order, id.inner())));
+
+        let mut ergebnis = Vec::with_capacity(auswahl.len());
+        while let Some(id) = stapel.pop() {
+            if !auswahl.contains(&id) {
+                continue;
+            }
+            let kinder = self.kinder_von(Some(id));
+            let mut kanal = self.kanaele[&id].clone();
+            kanal.child_count = kinder.len() as u32;
+            kanal.has_children = kinder.iter().any(|k| !auswahl.contains(k));
+            ergebnis.push(kanal);
+            stapel.extend(kinder.iter().rev());
+        }
+        ergebnis
+    }
+}
+
+/// Eltern eines Kanals, sofern er im Baum vorkommt
+fn eltern(kanaele: &HashMap<ChannelId, ChannelInfo>, kanal: &ChannelInfo) -> Option<ChannelId> {
+    kanal.parent_id.filter(|p| kanaele.contains_key(p))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uuid::Uuid;
+
+    fn id(n: u128) -> ChannelId {
+        ChannelId(Uuid::from_u128(n))
+    }
+
+    fn kanal(n: u128, parent: Option<u128>) -> ChannelInfo {
+        ChannelInfo {
+            channel_id: id(n),
+            name: format!("Kanal {n}"),
+            description: None,
+            parent_id: parent.map(id),
+            sort_order: 0,
+            max_clients: None,
+            current_clients: 0,
+            password_protected: false,
+            codec: "opus".into(),
+            codec_quality: 7,
+            has_children: false,
+            child_count: 0,
+            slow_mode_secs: 0,
+            edit_window_secs: 0,
+            join_by_approval: false,
+        }
+    }
+
+    fn ids(kanaele: &[ChannelInfo]) -> Vec<ChannelId> {
+        kanaele.iter().map(|k| k.channel_id).collect()
+    }
+
+    #[test]
+    fn teilbaum_ab_root_und_verwaiste_kanaele() {
+        // 3 verweist auf einen unbekannten Eltern und haengt an der Root
+        let baum = Kanalbaum::neu(vec![
+            kanal(2, Some(1)),
+            kanal(1, None),
+            kanal(3, Some(99)),
+            kanal(4, Some(2)),
+        ]);
+
+        let root_ebene = baum.teilbaum(None, 1);
+        assert_eq!(ids(&root_ebene), vec![id(1), id(3)]);
+        assert!(root_ebene[0].has_children);
+        assert_eq!(root_ebene[0].child_count, 1);
+
+        assert_eq!(ids(&baum.vollstaendig()), vec![id(1), id(2), id(4), id(3)]);
+        assert!(baum.teilbaum(Some(id(99)), 1).is_empty());
+    }
+
+    #[test]
+    fn snapshot_ohne_eigenen_kanal() {
+        let baum = Kanalbaum::neu(vec![kanal(1, None), kanal(2, Some(1)), kanal(3, Some(2))]);
+        assert_eq!(ids(&baum.snapshot(None)), vec![id(1)]);
+        assert_eq!(ids(&baum.snapshot(Some(id(3)))), vec![id(1), id(2), id(3)]);
+    }
+}
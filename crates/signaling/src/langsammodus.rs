@@ -0,0 +1,354 @@
+//! Langsam-Modus – Mindestabstand zwischen Chat-Nachrichten pro Kanal
+//!
+//! Moderatoren setzen pro Kanal `slow_mode_secs` (per `ChannelEdit` oder
+//! Commander). Danach darf jeder Benutzer in diesem Kanal hoechstens alle
+//! `slow_mode_secs` Sekunden eine Nachricht senden; zu fruehe Nachrichten
+//! werden vor dem Speichern mit `RateLimited` abgelehnt, die Restwartezeit
+//! steht in `retry_after_secs` der Fehlerdetails.
+//!
+//! Die Intervalle der Kanaele werden beim ersten Zugriff aus der Datenbank
+//! gelesen und hier zwischengespeichert; Aenderungen traegt
+//! [`crate::handlers::channel_handler::kanal_geaendert_melden`] nach. Die
+//! Zeitpunkte der letzten Nachrichten leben nur im Speicher und werden beim
+//! Verlassen des Kanals verworfen.
+//!
+//! Mitglieder mit ausdruecklich gewaehrtem [`LANGSAMMODUS_AUSNAHME`] sind
+//! ausgenommen.
+
+use dashmap::DashMap;
+use speakeasy_core::types::{ChannelId, UserId};
+use speakeasy_db::{
+    models::MAX_LANGSAMMODUS_SEK, repository::UserRepository, BanRepository, ChannelRepository,
+    ChatMessageRepository, PermissionRepository, ServerGroupRepository,
+};
+use std::collections::HashMap;
+use std::time::Duration;
+use tokio::time::Instant;
+
+use crate::error::{SignalingError, SignalingResult};
+use crate::notfall::ausdruecklich_gewaehrt;
+use crate::server_state::SignalingState;
+
+/// Berechtigung, die vom Langsam-Modus ausnimmt
+pub const LANGSAMMODUS_AUSNAHME: &str = "b_chat_slowmode_bypass";
+
+/// Intervalle der Kanaele und letzte Nachricht je Benutzer und Kanal
+#[derive(Default)]
+pub struct Langsammodus {
+    /// Zwischengespeicherte `slow_mode_secs` (0 = aus)
+    intervalle: DashMap<ChannelId, u32>,
+    /// Zeitpunkt der letzten angenommenen Nachricht
+    letzte: DashMap<UserId, HashMap<ChannelId, Instant>>,
+}
+
+impl Langsammodus {
+    pub fn neu() -> Self {
+        Self::default()
+    }
+
+    /// Zwischengespeichertes Intervall (`None` = noch nicht geladen)
+    pub fn intervall(&self, channel_id: &ChannelId) -> Option<u32> {
+        self.intervalle.get(channel_id).map(|s| *s)
+    }
+
+    /// Setzt das Intervall eines Kanals
+    pub fn intervall_setzen(&self, channel_id: ChannelId, sekunden: u32) {
+        self.intervalle.insert(channel_id, sekunden);
+    }
+
+    /// Vergisst einen geloeschten Kanal
+    pub fn kanal_entfernen(&self, channel_id: &ChannelId) {
+        self.intervalle.remove(channel_id);
+    }
+
+    /// Verbucht eine Nachricht oder nennt die Restwartezeit
+    ///
+    /// Eine abgelehnte Nachricht verlaengert die Wartezeit nicht.
+    pub fn pruefen(
+        &self,
+        user_id: UserId,
+        channel_id: ChannelId,
+        intervall: Duration,
+    ) -> Result<(), Duration> {
+        let jetzt = Instant::now();
+        let mut kanaele = self.letzte.entry(user_id).or_default();
+        if let Some(letzte) = kanaele.get(&channel_id) {
+            let vergangen = jetzt.duration_since(*letzte);
+            if vergangen < intervall {
+                return Err(intervall - vergangen);
+            }
+        }
+        kanaele.insert(channel_id, jetzt);
+        Ok(())
+    }
+
+    /// Verwirft alle Zeitpunkte eines Benutzers (Kanal verlassen, Trennen)
+    pub fn verlassen(&self, user_id: &UserId) {
+        self.letzte.remove(user_id);
+    }
+}
+
+/// Liefert `slow_mode_secs` eines Kanals (0 fuer unbekannte Kanaele)
+pub async fn intervall_laden<U, P, B>(state: &SignalingState<U, P, B>, channel_id: ChannelId) -> u32
+where
+    U: UserRepository + ServerGroupRepository + ChannelRepository + ChatMessageRepository + 'static,
+    P: PermissionRepository + 'static,
+    B: BanRepository + 'static,
+{
+    if let Some(sekunden) = state.langsammodus.intervall(&channel_id) {
+        return sekunden;
+    }
+    match ChannelRepository::get_by_id(state.db.as_ref(), channel_id.inner()).await {
+        Ok(kanal) => {
+            let sekunden = kanal.map_or(0, |k| {
+                k.slow_mode_secs.clamp(0, MAX_LANGSAMMODUS_SEK as i64) as u32
+            });
+            state.langsammodus.intervall_setzen(channel_id, sekunden);
+            sekunden
+        }
+        Err(e) => {
+            // Chat nicht blockieren, beim naechsten Mal erneut laden
+            tracing::warn!(channel_id = %channel_id, fehler = %e, "Langsam-Modus nicht ladbar");
+            0
+        }
+    }
+}
+
+/// Prueft vor dem Speichern, ob `user_id` in `channel_id` senden darf
+pub async fn chat_pruefen<U, P, B>(
+    state: &SignalingState<U, P, B>,
+    user_id: UserId,
+    channel_id: ChannelId,
+) -> SignalingResult<()>
+where
+    U: UserRepository + ServerGroupRepository + ChannelRepository + ChatMessageRepository + 'static,
+    P: PermissionRepository + 'static,
+    B: BanRepository + 'static,
+{
+    let sekunden = intervall_laden(state, channel_id).await;
+    if sekunden == 0
+        || ausdruecklich_gewaehrt(state, user_id, channel_id, LANGSAMMODUS_AUSNAHME).await
+    {
+        return Ok(());
+    }
+    state
+        .langsammodus
+        .pruefen(
+            user_id,
+            channel_id,
+            Duration::from_secs(u64::from(sekunden)),
+        )
+        .map_err(|warten| SignalingError::ZuHaeufig {
+            retry_after_secs: warten.as_secs_f64().ceil().max(1.0) as u64,
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::handlers::channel_handler::handle_channel_edit;
+    use crate::handlers::chat_handler::handle_chat_send;
+    use crate::test_hilfen::{test_state, TestState};
+    use speakeasy_db::models::{
+        BerechtigungsWert, BerechtigungsZiel, KanalUpdate, NeuerBenutzer, NeuerKanal, TriState,
+    };
+    use speakeasy_protocol::control::{
+        ChannelEditRequest, ChatSendRequest, ControlMessage, ControlPayload, ErrorCode,
+    };
+
+    const ZEHN_SEK: Duration = Duration::from_secs(10);
+
+    /// Kanal mit Langsam-Modus und ein Benutzer mit Datenbank-Eintrag
+    async fn szenario(state: &TestState, sekunden: i64) -> (ChannelId, UserId) {
+        let db = state.db.as_ref();
+        let kanal = ChannelRepository::create(
+            db,
+            NeuerKanal {
+                name: "Plaudern",
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        ChannelRepository::update(
+            db,
+            kanal.id,
+            KanalUpdate {
+                slow_mode_secs: Some(sekunden),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        let benutzer = UserRepository::create(
+            db,
+            NeuerBenutzer {
+                username: "plauderer",
+                password_hash: "hash",
+            },
+        )
+        .await
+        .unwrap();
+        (ChannelId(kanal.id), UserId(benutzer.id))
+    }
+
+    async fn senden(state: &TestState, user_id: UserId, kanal: ChannelId) -> ControlMessage {
+        let anfrage = ChatSendRequest {
+            channel_id: kanal,
+            content: "Hallo".into(),
+            reply_to: None,
+        };
+        handle_chat_send(anfrage, 1, user_id, state).await
+    }
+
+    #[tokio::test]
+    async fn zu_fruehe_nachricht_wird_mit_wartezeit_abgelehnt() {
+        let state = test_state().await;
+        let (kanal, user_id) = szenario(&state, 10).await;
+
+        let erste = senden(&state, user_id, kanal).await;
+        assert!(matches!(erste.payload, ControlPayload::ChatSendResponse(_)));
+        match senden(&state, user_id, kanal).await.payload {
+            ControlPayload::Error(fehler) => {
+                assert_eq!(fehler.code, ErrorCode::RateLimited);
+                assert_eq!(fehler.details.unwrap()["retry_after_secs"], 10);
+            }
+            andere => panic!("unerwartet: {andere:?}"),
+        }
+
+        // Verlassen setzt die Sperre zurueck
+        state.langsammodus.verlassen(&user_id);
+        let dritte = senden(&state, user_id, kanal).await;
+        assert!(matches!(
+            dritte.payload,
+            ControlPayload::ChatSendResponse(_)
+        ));
+    }
+
+    #[tokio::test]
+    async fn ausnahme_berechtigung_umgeht_den_langsam_modus() {
+        let state = test_state().await;
+        let (kanal, user_id) = szenario(&state, 10).await;
+        state
+            .db
+            .set_permission(
+                &BerechtigungsZiel::Benutzer(user_id.inner()),
+                LANGSAMMODUS_AUSNAHME,
+                BerechtigungsWert::TriState(TriState::Grant),
+                Some(kanal.inner()),
+            )
+            .await
+            .unwrap();
+
+        for _ in 0..3 {
+            let antwort = senden(&state, user_id, kanal).await;
+            assert!(matches!(
+                antwort.payload,
+                ControlPayload::ChatSendResponse(_)
+            ));
+        }
+    }
+
+    #[tokio::test]
+    async fn bearbeiten_setzt_intervall_und_meldet_kanal() {
+        let state = test_state().await;
+        let (kanal, user_id) = szenario(&state, 0).await;
+        assert_eq!(intervall_laden(&state, kanal).await, 0);
+        let mut rx = state.broadcaster.client_registrieren(user_id);
+
+        let anfrage = ChannelEditRequest {
+            channel_id: kanal,
+            name: None,
+            description: None,
+            password: None,
+            max_clients: None,
+            sort_order: None,
+            slow_mode_secs: Some(5),
+            join_by_approval: None,
+        };
+        handle_channel_edit(anfrage, 1, user_id, &state).await;
+
+        assert_eq!(state.langsammodus.intervall(&kanal), Some(5));
+        match rx.try_recv().unwrap().payload {
+            ControlPayload::ChannelEdited(event) => {
+                assert_eq!(event.channel.channel_id, kanal);
+                assert_eq!(event.channel.slow_mode_secs, 5);
+            }
+            andere => panic!("unerwartet: {andere:?}"),
+        }
+        let erste = senden(&state, user_id, kanal).await;
+        assert!(matches!(erste.payload, ControlPayload::ChatSendResponse(_)));
+        assert!(matches!(
+            senden(&state, user_id, kanal).await.payload,
+            ControlPayload::Error(_)
+        ));
+    }
+
+    #[tokio::test]
+    async fn zu_langes_intervall_wird_abgelehnt() {
+        let state = test_state().await;
+        let (kanal, user_id) = szenario(&state, 0).await;
+
+        let anfrage = ChannelEditRequest {
+            channel_id: kanal,
+            name: None,
+            description: None,
+            password: None,
+            max_clients: None,
+            sort_order: None,
+            slow_mode_secs: Some(MAX_LANGSAMMODUS_SEK + 1),
+            join_by_approval: None,
+        };
+        let antwort = handle_channel_edit(anfrage, 1, user_id, &state).await;
+        assert!(matches!(antwort.payload, ControlPayload::Error(_)));
+        assert_eq!(intervall_laden(&state, kanal).await, 0);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn sperre_endet_genau_nach_dem_intervall() {
+        let modus = Langsammodus::neu();
+        let (a, kanal) = (UserId::new(), ChannelId::new());
+
+        assert_eq!(modus.pruefen(a, kanal, ZEHN_SEK), Ok(()));
+        tokio::time::advance(Duration::from_millis(9_999)).await;
+        assert_eq!(
+            modus.pruefen(a, kanal, ZEHN_SEK),
+            Err(Duration::from_millis(1))
+        );
+        tokio::time::advance(Duration::from_millis(1)).await;
+        assert_eq!(modus.pruefen(a, kanal, ZEHN_SEK), Ok(()));
+        // Die neue Nachricht startet die naechste Sperre
+        assert_eq!(modus.pruefen(a, kanal, ZEHN_SEK), Err(ZEHN_SEK));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn abgelehnte_nachrichten_verlaengern_nicht() {
+        let modus = Langsammodus::neu();
+        let (a, kanal) = (UserId::new(), ChannelId::new());
+
+        assert_eq!(modus.pruefen(a, kanal, ZEHN_SEK), Ok(()));
+        tokio::time::advance(Duration::from_secs(4)).await;
+        assert_eq!(
+            modus.pruefen(a, kanal, ZEHN_SEK),
+            Err(Duration::from_secs(6))
+        );
+        tokio::time::advance(Duration::from_secs(6)).await;
+        assert_eq!(modus.pruefen(a, kanal, ZEHN_SEK), Ok(()));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn sperre_gilt_je_benutzer_und_kanal() {
+        let modus = Langsammodus::neu();
+        let (a, b) = (UserId::new(), UserId::new());
+        let (lobby, spiele) = (ChannelId::new(), ChannelId::new());
+
+        assert_eq!(modus.pruefen(a, lobby, ZEHN_SEK), Ok(()));
+        assert_eq!(modus.pruefen(b, lobby, ZEHN_SEK), Ok(()));
+        assert_eq!(modus.pruefen(a, spiele, ZEHN_SEK), Ok(()));
+        assert!(modus.pruefen(a, lobby, ZEHN_SEK).is_err());
+
+        modus.verlassen(&a);
+        assert_eq!(modus.pruefen(a, lobby, ZEHN_SEK), Ok(()));
+        assert!(modus.pruefen(b, lobby, ZEHN_SEK).is_err());
+    }
+}
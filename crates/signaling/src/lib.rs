@@ -14,27 +14,67 @@
 //!     |  State Machine: Connected -> Authenticating -> Authenticated -> InChannel
 //!     |
 //!     v
-//! MessageDispatcher
+//! MessageDispatcher (begrenzt gleichzeitige Anfragen und drosselt die Rate,
+//!     |              siehe `anfragelimit` und `drosselung`)
 //!     |
 //!     +-- AuthHandler      (Login, Logout, Session)
-//!     +-- ChannelHandler   (Join, Leave, Create, Delete, Edit)
+//!     +-- ChannelHandler   (List, Expand, Join, Members, Leave, Create, Delete, Edit)
 //!     +-- ClientHandler    (List, Kick, Ban, Move, Poke)
-//!     +-- ServerHandler    (Info, Edit, Stop)
+//!     +-- ServerHandler    (Info, Edit, Stop, Mitschnitt-Freigabe)
 //!     +-- VoiceHandler     (Init, Ready, Disconnect)
 //!     +-- PermissionHandler (List, Add, Remove)
+//!     +-- E2EHandler       (Schluesselverteilung weiterleiten)
 //!
 //! PresenceManager  – Wer ist online, in welchem Channel
 //! EventBroadcaster – Events an alle relevanten Clients senden
+//! AFK-Pruefung     – Verschiebt inaktive Clients in den AFK-Kanal
+//! Sprecher         – Meldet Sprechwechsel gedrosselt an den Kanal
+//! Notfall          – Notfall-Stummschaltung ganzer Kanaele
+//! Kanalbaum        – Teilbaeume fuer Server mit sehr vielen Kanaelen
+//! Mitglieder       – Gekuerzte Beitrittsantworten fuer sehr volle Kanaele
+//! Moderation       – Kick, Move und Poke im Auftrag des Commanders
+//! Bereinigung      – Spam eines Benutzers stapelweise entfernen (z.B. beim Ban)
+//! Soundboard       – Kurze Clips serverseitig in Kanaele einspielen
+//! Einladung        – Kanal-Einladungen und Anklopfen bei Freigabe-Kanaelen
+//! Ankuendigung     – Betriebsalarme an verbundene Administratoren
+//! Langsam-Modus    – Mindestabstand zwischen Chat-Nachrichten pro Kanal
+//! Schluesselrotation – Neue E2E-Gruppenschluessel bei Mitgliederwechseln
+//! Trennung         – Kicks und Server-Stopps mit Countdown und Abbruch
+//! Sitzungsablauf   – Warnt vor dem Ablauf der Session, bis der Client verlaengert
+//! Mitschnitt       – Eingehende Nachrichten einer Sitzung zur Fehlersuche aufzeichnen
+//! Wiedergabe       – Mitschnitte gegen einen Server im Prozess abspielen
 //! ```
 
+pub mod afk;
+pub mod anfragelimit;
+pub mod ankuendigung;
+pub mod bearbeitungsfrist;
+pub mod bereinigung;
 pub mod broadcast;
 pub mod connection;
 pub mod dispatcher;
+pub mod drosselung;
+pub mod einladung;
 pub mod error;
 pub mod handlers;
+pub mod kanalbaum;
+pub mod langsammodus;
+pub mod mitglieder;
+pub mod mitschnitt;
+pub mod moderation;
+pub mod notfall;
 pub mod presence;
+pub mod schluesselrotation;
 pub mod server_state;
+pub mod sitzungsablauf;
+pub mod soundboard;
+pub mod sprecher;
 pub mod tcp;
+pub mod trennung;
+pub mod wiedergabe;
+
+#[cfg(test)]
+pub(crate) mod test_hilfen;
 
 // Bequeme Re-Exporte
 pub use broadcast::EventBroadcaster;
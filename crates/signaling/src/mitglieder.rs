@@ -0,0 +1,239 @@
+//! Mitgliederlisten fuer Kanalbeitritte
+//!
+//! In kleinen Kanaelen enthaelt die `ChannelJoinResponse` alle Mitglieder.
+//! Oberhalb von [`SignalingConfig::mitglieder_teilweise_ab`] nur den
+//! Beitretenden und die [`SignalingConfig::mitglieder_vorschau`] zuletzt
+//! aktiven Mitglieder; den Rest laedt der Client per `ChannelMembers`
+//! seitenweise nach.
+//!
+//! Aus dem `PresenceManager` werden zuerst nur die User-IDs kopiert. Ganze
+//! Eintraege werden nur fuer die ausgewaehlten Mitglieder kopiert, und
+//! serialisiert wird erst beim Versand, ohne gehaltene Sperren.
+//!
+//! [`SignalingConfig::mitglieder_teilweise_ab`]: crate::server_state::SignalingConfig::mitglieder_teilweise_ab
+//! [`SignalingConfig::mitglieder_vorschau`]: crate::server_state::SignalingConfig::mitglieder_vorschau
+
+use std::cmp::Reverse;
+use std::sync::Arc;
+
+use speakeasy_core::types::{ChannelId, UserId};
+use speakeasy_db::{
+    repository::UserRepository, BanRepository, ChannelRepository, ChatMessageRepository,
+    PermissionRepository, ServerGroupRepository,
+};
+use speakeasy_protocol::control::{
+    ChannelJoinResponse, ChannelMembersRequest, ChannelMembersResponse, ClientInfo,
+};
+use tokio::time::Instant;
+
+use crate::handlers::client_handler::client_info_aus_presence;
+use crate::server_state::SignalingState;
+
+/// Standard-Schwelle, oberhalb der die Beitrittsantwort gekuerzt wird
+pub const STANDARD_TEILWEISE_AB: usize = 250;
+
+/// Standardanzahl zuletzt aktiver Mitglieder in einer gekuerzten Antwort
+pub const STANDARD_VORSCHAU: usize = 50;
+
+/// Seitengroesse ohne `limit` in der Anfrage
+pub const STANDARD_SEITE: usize = 200;
+
+/// Groesste ausgelieferte Seite
+pub const MAX_SEITE: usize = 500;
+
+/// Auswahl der Mitglieder fuer eine Beitrittsantwort
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Vorschau {
+    /// Aufzunehmende Mitglieder in Ausgabereihenfolge
+    pub user_ids: Vec<UserId>,
+    /// Anzahl aller Mitglieder
+    pub anzahl: usize,
+    /// `user_ids` ist unvollstaendig
+    pub teilweise: bool,
+}
+
+/// Waehlt die Mitglieder fuer die Beitrittsantwort aus
+///
+/// Bis zur Schwelle (0 = nie kuerzen) alle ausser `selbst`. Darueber
+/// `selbst` zuerst, danach die `vorschau` zuletzt aktiven Mitglieder;
+/// Mitglieder ohne erfasste Aktivitaet kommen zuletzt.
+pub fn vorschau_waehlen(
+    mitglieder: Vec<UserId>,
+    selbst: UserId,
+    schwelle: usize,
+    vorschau: usize,
+    letzte_aktivitaet: impl Fn(&UserId) -> Option<Instant>,
+) -> Vorschau {
+    let anzahl = mitglieder.len();
+    let andere: Vec<UserId> = mitglieder.into_iter().filter(|id| *id != selbst).collect();
+    if schwelle == 0 || anzahl <= schwelle {
+        return Vorschau {
+            user_ids: andere,
+            anzahl,
+            teilweise: false,
+        };
+    }
+
+    let mut nach_aktivitaet: Vec<(Option<Instant>, UserId)> = andere
+        .into_iter()
+        .map(|id| (letzte_aktivitaet(&id), id))
+        .collect();
+    nach_aktivitaet.sort_unstable_by_key(|(zeitpunkt, id)| (Reverse(*zeitpunkt), id.inner()));
+
+    let mut user_ids = Vec::with_capacity(vorschau + 1);
+    user_ids.push(selbst);
+    user_ids.extend(nach_aktivitaet.into_iter().take(vorschau).map(|(_, id)| id));
+    Vorschau {
+        user_ids,
+        anzahl,
+        teilweise: true,
+    }
+}
+
+/// Eine Seite der nach User-ID sortierten Mitglieder nach `nach`
+///
+/// Gibt die Seite und den Cursor fuer die naechste zurueck (None = Ende).
+pub fn seite(
+    mut mitglieder: Vec<UserId>,
+    nach: Option<UserId>,
+    limit: usize,
+) -> (Vec<UserId>, Option<UserId>) {
+    mitglieder.sort_unstable_by_key(|id| id.inner());
+    let start = nach.map_or(0, |nach| {
+        mitglieder.partition_point(|id| id.inner() <= nach.inner())
+    });
+    let ende = start.saturating_add(limit.max(1)).min(mitglieder.len());
+    let naechste = (ende < mitglieder.len()).then(|| mitglieder[ende - 1]);
+    (mitglieder[start..ende].to_vec(), naechste)
+}
+
+/// Baut die Beitrittsantwort fuer `user_id` im Kanal `channel_id`
+pub fn beitritts_antwort<U, P, B>(
+    state: &Arc<SignalingState<U, P, B>>,
+    user_id: UserId,
+    channel_id: ChannelId,
+    listen_only: bool,
+) -> ChannelJoinResponse
+where
+    U: UserRepository + ServerGroupRepository + ChannelRepository + ChatMessageRepository + 'static,
+    P: PermissionRepository + 'static,
+    B: BanRepository + 'static,
+{
+    let mitglieder = state.presence.user_ids_in_channel(&channel_id);
+    let auswahl = vorschau_waehlen(
+        mitglieder,
+        user_id,
+        state.config.mitglieder_teilweise_ab,
+        state.config.mitglieder_vorschau,
+        |id| state.aktivitaet.letzte_aktivitaet(id),
+    );
+    let mut clients = client_infos(state, &auswahl.user_ids);
+    // Laufende Soundboard-Wiedergaben, damit spaet Beitretende die SSRC kennen
+    clients.extend(state.soundboard.clients_in(&channel_id));
+    ChannelJoinResponse {
+        channel_id,
+        clients,
+        member_count: auswahl.anzahl as u32,
+        members_partial: auswahl.teilweise,
+        listen_only,
+        speaking: crate::sprecher::aktive_sprecher(state, &channel_id),
+        // Nur der zwischengespeicherte Wert; der Join-Handler laedt nach
+        slow_mode_secs: state.langsammodus.intervall(&channel_id).unwrap_or(0),
+    }
+}
+
+/// Beantwortet eine `ChannelMembers`-Anfrage
+pub fn seite_laden<U, P, B>(
+    state: &Arc<SignalingState<U, P, B>>,
+    request: &ChannelMembersRequest,
+) -> ChannelMembersResponse
+where
+    U: UserRepository + ServerGroupRepository + ChannelRepository + ChatMessageRepository + 'static,
+    P: PermissionRepository + 'static,
+    B: BanRepository + 'static,
+{
+    let mitglieder = state.presence.user_ids_in_channel(&request.channel_id);
+    let total = mitglieder.len() as u32;
+    let limit = request
+        .limit
+        .map_or(STANDARD_SEITE, |l| l as usize)
+        .min(MAX_SEITE);
+    let (user_ids, next_after) = seite(mitglieder, request.after, limit);
+    ChannelMembersResponse {
+        channel_id: request.channel_id,
+        clients: client_infos(state, &user_ids),
+        total,
+        next_after,
+    }
+}
+
+/// Client-Infos der noch verbundenen Benutzer in gegebener Reihenfolge
+fn client_infos<U, P, B>(
+    state: &Arc<SignalingState<U, P, B>>,
+    user_ids: &[UserId],
+) -> Vec<ClientInfo>
+where
+    U: UserRepository + ServerGroupRepository + ChannelRepository + ChatMessageRepository + 'static,
+    P: PermissionRepository + 'static,
+    B: BanRepository + 'static,
+{
+    user_ids
+        .iter()
+        .filter_map(|id| state.presence.client_presence(id))
+        .map(|p| client_info_aus_presence(&p, &state.voice_state))
+        .collect()
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    use uuid::Uuid;
+
+    fn user(n: u128) -> UserId {
+        UserId(Uuid::from_u128(n))
+    }
+
+    #[test]
+    fn kleine_kanaele_vollstaendig_ohne_selbst() {
+        let mitglieder = (1..=5).map(user).collect();
+        let auswahl = vorschau_waehlen(mitglieder, user(3), 10, 2, |_| None);
+        assert!(!auswahl.teilweise);
+        assert_eq!(auswahl.anzahl, 5);
+        assert_eq!(auswahl.user_ids, [user(1), user(2), user(4), user(5)]);
+    }
+
+    #[test]
+    fn vorschau_nimmt_zuletzt_aktive() {
+        let basis = Instant::now();
+        let mitglieder = (1..=6).map(user).collect();
+        // user(n) war n Sekunden nach `basis` aktiv, user(6) nie
+        let auswahl = vorschau_waehlen(mitglieder, user(1), 3, 2, |id| {
+            let n = id.inner().as_u128() as u64;
+            (n < 6).then(|| basis + Duration::from_secs(n))
+        });
+        assert!(auswahl.teilweise);
+        assert_eq!(auswahl.anzahl, 6);
+        assert_eq!(auswahl.user_ids, [user(1), user(5), user(4)]);
+    }
+
+    #[test]
+    fn seiten_ueberspringen_niemanden() {
+        let mitglieder: Vec<UserId> = (1..=7).rev().map(user).collect();
+        let (erste, cursor) = seite(mitglieder.clone(), None, 3);
+        assert_eq!(erste, [user(1), user(2), user(3)]);
+        assert_eq!(cursor, Some(user(3)));
+
+        // user(3) ist inzwischen gegangen: der Cursor bleibt gueltig
+        let ohne_drei: Vec<UserId> = mitglieder.into_iter().filter(|id| *id != user(3)).collect();
+        let (zweite, cursor) = seite(ohne_drei.clone(), cursor, 3);
+        assert_eq!(zweite, [user(4), user(5), user(6)]);
+        let (letzte, cursor) = seite(ohne_drei, cursor, 3);
+        assert_eq!(letzte, [user(7)]);
+        assert_eq!(cursor, None);
+    }
+}
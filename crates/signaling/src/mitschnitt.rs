@@ -0,0 +1,507 @@
+//! Sitzungsmitschnitt – eingehende Control-Nachrichten einer Verbindung aufzeichnen
+//!
+//! Fehlerberichte der Art "nach diesen fuenf Schritten war der Kanalbaum
+//! kaputt" lassen sich schwer nachstellen. Der Server kann deshalb die
+//! eingehenden Nachrichten einer Verbindung mit Zeitversatz und den
+//! Antworten darauf als NDJSON in eine Datei schreiben;
+//! [`crate::wiedergabe`] spielt solche Dateien gegen einen Server im
+//! Prozess ab.
+//!
+//! Mitgeschnitten wird nur mit konfiguriertem Verzeichnis und dann
+//! - fuer Benutzer, die ein Admin per `SessionCapture` freigegeben hat und
+//!   die beim Login selbst `capture_consent` setzen (ab diesem Login), oder
+//! - fuer alle Verbindungen, wenn `alle_verbindungen` gesetzt ist; das
+//!   wirkt nur in Debug-Builds
+//!
+//! ## Format
+//! Die erste Zeile ist ein [`MitschnittKopf`], danach folgt je eingehender
+//! Nachricht ein [`MitschnittEintrag`]. Passwoerter, API- und Session-Tokens
+//! werden vor dem Schreiben durch [`GESCHWAERZT`] ersetzt.
+
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::time::Instant;
+
+use chrono::{DateTime, Utc};
+use dashmap::DashSet;
+use serde::{Deserialize, Serialize};
+use speakeasy_core::types::UserId;
+use speakeasy_protocol::control::{ControlMessage, ControlPayload, ProtokollVersion};
+
+/// Kennung in [`MitschnittKopf::format`]
+pub const FORMAT_NAME: &str = "speakeasy-mitschnitt";
+/// Aktuelle Version des Dateiformats
+pub const FORMAT_VERSION: u32 = 1;
+/// Ersatz fuer geschwaerzte Zugangsdaten
+pub const GESCHWAERZT: &str = "<geschwaerzt>";
+
+/// So viele Nachrichten vor dem Login werden vorgehalten; wer sich bis
+/// dahin nicht anmeldet, wird nicht mitgeschnitten
+const MAX_VORLAUF: usize = 16;
+
+/// Einstellungen fuer Sitzungsmitschnitte
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MitschnittKonfig {
+    /// Zielverzeichnis (`None` = keine Mitschnitte)
+    pub verzeichnis: Option<PathBuf>,
+    /// Jede Verbindung mitschneiden (nur in Debug-Builds wirksam)
+    pub alle_verbindungen: bool,
+}
+
+/// Erste Zeile einer Mitschnitt-Datei
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MitschnittKopf {
+    pub format: String,
+    pub version: u32,
+    /// Protokollversion des aufzeichnenden Servers
+    pub protokoll_version: String,
+    pub beginn: DateTime<Utc>,
+    /// Mitgeschnittener Benutzer (bei `alle_verbindungen` unbekannt)
+    #[serde(default)]
+    pub user_id: Option<UserId>,
+}
+
+impl MitschnittKopf {
+    pub fn neu(user_id: Option<UserId>) -> Self {
+        Self {
+            format: FORMAT_NAME.into(),
+            version: FORMAT_VERSION,
+            protokoll_version: ProtokollVersion::AKTUELL.to_string(),
+            beginn: Utc::now(),
+            user_id,
+        }
+    }
+}
+
+/// Eine eingehende Nachricht mit ihren Antworten
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MitschnittEintrag {
+    /// Verbindung innerhalb der Datei; der Server schreibt je Verbindung
+    /// eine Datei (immer `0`), zusammengefuehrte Mitschnitte nummerieren
+    #[serde(default)]
+    pub verbindung: u32,
+    /// Millisekunden seit Beginn des Mitschnitts
+    pub versatz_ms: u64,
+    pub nachricht: ControlMessage,
+    /// Antworten des Servers (Zwischenmeldungen zuerst); die Wiedergabe
+    /// ordnet daran neu vergebene IDs zu
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub antworten: Vec<ControlMessage>,
+}
+
+/// Fehler beim Lesen einer Mitschnitt-Datei
+#[derive(Debug, thiserror::Error)]
+pub enum MitschnittFehler {
+    #[error("Mitschnitt ist leer")]
+    Leer,
+    #[error("Zeile {zeile}: {grund}")]
+    Format { zeile: usize, grund: String },
+    #[error("Unbekanntes Format '{format}' Version {version}")]
+    Version { format: String, version: u32 },
+}
+
+/// Ein vollstaendig gelesener Mitschnitt
+#[derive(Debug, Clone)]
+pub struct Mitschnitt {
+    pub kopf: MitschnittKopf,
+    pub eintraege: Vec<MitschnittEintrag>,
+}
+
+impl Mitschnitt {
+    /// Liest einen Mitschnitt im NDJSON-Format (Leerzeilen werden ignoriert)
+    pub fn lesen(text: &str) -> Result<Self, MitschnittFehler> {
+        let mut zeilen = text
+            .lines()
+            .enumerate()
+            .filter(|(_, zeile)| !zeile.trim().is_empty());
+
+        let (_, erste) = zeilen.next().ok_or(MitschnittFehler::Leer)?;
+        let kopf: MitschnittKopf =
+            serde_json::from_str(erste).map_err(|e| MitschnittFehler::Format {
+                zeile: 1,
+                grund: e.to_string(),
+            })?;
+        if kopf.format != FORMAT_NAME || kopf.version != FORMAT_VERSION {
+            return Err(MitschnittFehler::Version {
+                format: kopf.format,
+                version: kopf.version,
+            });
+        }
+
+        let eintraege = zeilen
+            .map(|(index, zeile)| {
+                serde_json::from_str(zeile).map_err(|e| MitschnittFehler::Format {
+                    zeile: index + 1,
+                    grund: e.to_string(),
+                })
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self { kopf, eintraege })
+    }
+}
+
+/// Ersetzt Zugangsdaten einer Nachricht durch [`GESCHWAERZT`]
+///
+/// Die Wiedergabe legt Benutzer mit diesem Passwort an; geschwaerzte
+/// Kanal-Passwoerter bleiben untereinander gleich und passen daher weiter.
+pub fn schwaerzen(nachricht: &ControlMessage) -> ControlMessage {
+    let mut nachricht = nachricht.clone();
+    let ersetzen = |wert: &mut Option<String>| {
+        if wert.is_some() {
+            *wert = Some(GESCHWAERZT.into());
+        }
+    };
+    match &mut nachricht.payload {
+        ControlPayload::Login(anfrage) => {
+            anfrage.password = GESCHWAERZT.into();
+            // Token-Logins lassen sich nicht nachstellen: als Passwort-Login aufzeichnen
+            anfrage.token = None;
+        }
+        ControlPayload::LoginResponse(antwort) => {
+            antwort.session_token = GESCHWAERZT.into();
+        }
+        ControlPayload::Register(anfrage) => {
+            anfrage.password = GESCHWAERZT.into();
+            ersetzen(&mut anfrage.invite_code);
+        }
+        ControlPayload::PasswordChange(anfrage) => {
+            anfrage.old_password = GESCHWAERZT.into();
+            anfrage.new_password = GESCHWAERZT.into();
+        }
+        ControlPayload::AccountDelete(anfrage) => {
+            anfrage.password_confirmation = GESCHWAERZT.into();
+        }
+        ControlPayload::ChannelJoin(anfrage) => ersetzen(&mut anfrage.password),
+        ControlPayload::ChannelCreate(anfrage) => ersetzen(&mut anfrage.password),
+        ControlPayload::ChannelEdit(anfrage) => ersetzen(&mut anfrage.password),
+        _ => {}
+    }
+    nachricht
+}
+
+/// Freigaben fuer Sitzungsmitschnitte (serverweit)
+pub struct Mitschnitte {
+    konfig: MitschnittKonfig,
+    freigegeben: DashSet<UserId>,
+}
+
+impl Mitschnitte {
+    pub fn neu(konfig: MitschnittKonfig) -> Self {
+        if konfig.alle_verbindungen && konfig.verzeichnis.is_some() {
+            if cfg!(debug_assertions) {
+                tracing::warn!("Alle Verbindungen werden mitgeschnitten");
+            } else {
+                tracing::warn!("Mitschnitt aller Verbindungen wird in Release-Builds ignoriert");
+            }
+        }
+        Self {
+            konfig,
+            freigegeben: DashSet::new(),
+        }
+    }
+
+    /// Gibt den Mitschnitt fuer einen Benutzer frei oder beendet ihn
+    ///
+    /// Laufende Mitschnitte enden mit der naechsten Nachricht; neue
+    /// beginnen erst beim naechsten Login.
+    pub fn freigeben(&self, user_id: UserId, aktiv: bool) {
+        if aktiv {
+            self.freigegeben.insert(user_id);
+        } else {
+            self.freigegeben.remove(&user_id);
+        }
+    }
+
+    pub fn ist_freigegeben(&self, user_id: &UserId) -> bool {
+        self.freigegeben.contains(user_id)
+    }
+
+    /// Ob ueberhaupt Mitschnitte geschrieben werden
+    pub fn speichert(&self) -> bool {
+        self.konfig.verzeichnis.is_some()
+    }
+
+    fn alle_verbindungen(&self) -> bool {
+        self.konfig.alle_verbindungen && cfg!(debug_assertions)
+    }
+
+    fn datei_oeffnen(&self, peer: SocketAddr) -> std::io::Result<(PathBuf, BufWriter<File>)> {
+        let verzeichnis = self
+            .konfig
+            .verzeichnis
+            .as_ref()
+            .ok_or_else(|| std::io::Error::other("kein Mitschnitt-Verzeichnis"))?;
+        std::fs::create_dir_all(verzeichnis)?;
+        let name = format!(
+            "{}-{}.ndjson",
+            Utc::now().format("%Y%m%dT%H%M%S%.3f"),
+            peer.to_string().replace([':', '.', '[', ']'], "_")
+        );
+        let pfad = verzeichnis.join(name);
+        Ok((pfad.clone(), BufWriter::new(File::create(pfad)?)))
+    }
+}
+
+enum Zustand {
+    /// Noch nicht angemeldet: Nachrichten vorhalten
+    Vorlauf(Vec<MitschnittEintrag>),
+    Aktiv {
+        datei: BufWriter<File>,
+        /// Freigegebener Benutzer (`None` = alle Verbindungen)
+        user_id: Option<UserId>,
+    },
+    Aus,
+}
+
+/// Mitschnitt einer einzelnen Verbindung
+pub struct SitzungsMitschnitt {
+    peer: SocketAddr,
+    start: Instant,
+    einwilligung: bool,
+    zustand: Zustand,
+}
+
+impl SitzungsMitschnitt {
+    pub fn neu(mitschnitte: &Mitschnitte, peer: SocketAddr) -> Self {
+        let mut mitschnitt = Self {
+            peer,
+            start: Instant::now(),
+            einwilligung: false,
+            zustand: Zustand::Aus,
+        };
+        if !mitschnitte.speichert() {
+            return mitschnitt;
+        }
+        mitschnitt.zustand = if mitschnitte.alle_verbindungen() {
+            mitschnitt.beginnen(mitschnitte, None, Vec::new())
+        } else {
+            Zustand::Vorlauf(Vec::new())
+        };
+        mitschnitt
+    }
+
+    /// Ob noch aufgezeichnet wird (oder auf den Login gewartet)
+    pub fn aktiv(&self) -> bool {
+        !matches!(self.zustand, Zustand::Aus)
+    }
+
+    /// Zeichnet eine verarbeitete Nachricht mit ihren Antworten auf
+    ///
+    /// `user_id` ist der Benutzer der Verbindung nach der Verarbeitung.
+    pub fn aufzeichnen(
+        &mut self,
+        mitschnitte: &Mitschnitte,
+        nachricht: &ControlMessage,
+        antworten: &[ControlMessage],
+        user_id: Option<UserId>,
+    ) {
+        if !self.aktiv() {
+            return;
+        }
+        if let ControlPayload::Login(anfrage) = &nachricht.payload {
+            self.einwilligung = anfrage.capture_consent;
+        }
+        let eintrag = MitschnittEintrag {
+            verbindung: 0,
+            versatz_ms: self.start.elapsed().as_millis() as u64,
+            nachricht: schwaerzen(nachricht),
+            antworten: antworten.iter().map(schwaerzen).collect(),
+        };
+
+        self.zustand = match std::mem::replace(&mut self.zustand, Zustand::Aus) {
+            Zustand::Vorlauf(mut vorlauf) => match user_id {
+                None if vorlauf.len() < MAX_VORLAUF => {
+                    vorlauf.push(eintrag);
+                    Zustand::Vorlauf(vorlauf)
+                }
+                None => Zustand::Aus,
+                Some(uid) if self.einwilligung && mitschnitte.ist_freigegeben(&uid) => {
+                    vorlauf.push(eintrag);
+                    self.beginnen(mitschnitte, Some(uid), vorlauf)
+                }
+                Some(_) => Zustand::Aus,
+            },
+            Zustand::Aktiv {
+                user_id: Some(uid), ..
+            } if !mitschnitte.ist_freigegeben(&uid) => {
+                tracing::info!(user_id = %uid, "Sitzungsmitschnitt beendet");
+                Zustand::Aus
+            }
+            Zustand::Aktiv { mut datei, user_id } => match zeile_schreiben(&mut datei, &eintrag) {
+                Ok(()) => Zustand::Aktiv { datei, user_id },
+                Err(e) => {
+                    tracing::warn!(peer = %self.peer, fehler = %e, "Mitschnitt abgebrochen");
+                    Zustand::Aus
+                }
+            },
+            Zustand::Aus => Zustand::Aus,
+        };
+    }
+
+    fn beginnen(
+        &self,
+        mitschnitte: &Mitschnitte,
+        user_id: Option<UserId>,
+        vorlauf: Vec<MitschnittEintrag>,
+    ) -> Zustand {
+        let ergebnis = mitschnitte
+            .datei_oeffnen(self.peer)
+            .and_then(|(pfad, mut datei)| {
+                zeile_schreiben(&mut datei, &MitschnittKopf::neu(user_id))?;
+                for eintrag in &vorlauf {
+                    zeile_schreiben(&mut datei, eintrag)?;
+                }
+                Ok((pfad, datei))
+            });
+        match ergebnis {
+            Ok((pfad, datei)) => {
+                tracing::info!(
+                    peer = %self.peer,
+                    user_id = ?user_id,
+                    datei = %pfad.display(),
+                    "Sitzungsmitschnitt gestartet"
+                );
+                Zustand::Aktiv { datei, user_id }
+            }
+            Err(e) => {
+                tracing::warn!(peer = %self.peer, fehler = %e, "Mitschnitt nicht moeglich");
+                Zustand::Aus
+            }
+        }
+    }
+}
+
+fn zeile_schreiben<T: Serialize>(datei: &mut BufWriter<File>, wert: &T) -> std::io::Result<()> {
+    serde_json::to_writer(&mut *datei, wert)?;
+    datei.write_all(b"\n")?;
+    datei.flush()
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use speakeasy_protocol::control::{ChannelJoinRequest, LoginRequest};
+
+    fn login(einwilligung: bool) -> ControlMessage {
+        ControlMessage::new(
+            1,
+            ControlPayload::Login(LoginRequest {
+                username: "anna".into(),
+                password: "streng-geheim".into(),
+                token: Some("api-token".into()),
+                client_version: "test".into(),
+                display_name: None,
+                capture_consent: einwilligung,
+            }),
+        )
+    }
+
+    fn verzeichnis(name: &str) -> PathBuf {
+        let pfad = std::env::temp_dir().join(format!("speakeasy-{name}-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&pfad).unwrap();
+        pfad
+    }
+
+    fn dateien(verzeichnis: &PathBuf) -> Vec<String> {
+        std::fs::read_dir(verzeichnis)
+            .unwrap()
+            .map(|d| std::fs::read_to_string(d.unwrap().path()).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn zugangsdaten_werden_geschwaerzt() {
+        let geschwaerzt = schwaerzen(&login(false));
+        let json = serde_json::to_string(&geschwaerzt).unwrap();
+        assert!(!json.contains("streng-geheim"));
+        assert!(!json.contains("api-token"));
+        let ControlPayload::Login(anfrage) = geschwaerzt.payload else {
+            panic!("Login erwartet");
+        };
+        assert_eq!(anfrage.password, GESCHWAERZT);
+        assert_eq!(anfrage.username, "anna");
+
+        let beitritt = schwaerzen(&ControlMessage::new(
+            2,
+            ControlPayload::ChannelJoin(ChannelJoinRequest {
+                channel_id: speakeasy_core::types::ChannelId::new(),
+                password: Some("kanal-pw".into()),
+                listen_only: false,
+            }),
+        ));
+        assert!(!serde_json::to_string(&beitritt)
+            .unwrap()
+            .contains("kanal-pw"));
+    }
+
+    #[test]
+    fn mitschnitt_nur_mit_freigabe_und_einwilligung() {
+        let pfad = verzeichnis("mitschnitt");
+        let mitschnitte = Mitschnitte::neu(MitschnittKonfig {
+            verzeichnis: Some(pfad.clone()),
+            alle_verbindungen: false,
+        });
+        let anna = UserId::new();
+        let peer = "127.0.0.1:4000".parse().unwrap();
+
+        // Freigegeben, aber ohne Einwilligung
+        mitschnitte.freigeben(anna, true);
+        let mut ohne = SitzungsMitschnitt::neu(&mitschnitte, peer);
+        ohne.aufzeichnen(&mitschnitte, &login(false), &[], Some(anna));
+        assert!(dateien(&pfad).is_empty());
+
+        // Einwilligung und Freigabe: Vorlauf und Login landen in der Datei
+        let mut mit = SitzungsMitschnitt::neu(&mitschnitte, peer);
+        let hallo = ControlMessage::new(0, ControlPayload::ServerInfo);
+        mit.aufzeichnen(&mitschnitte, &hallo, &[], None);
+        mit.aufzeichnen(&mitschnitte, &login(true), &[], Some(anna));
+        mit.aufzeichnen(&mitschnitte, &hallo, &[], Some(anna));
+
+        // Freigabe entzogen: keine weiteren Zeilen
+        mitschnitte.freigeben(anna, false);
+        mit.aufzeichnen(&mitschnitte, &hallo, &[], Some(anna));
+
+        let inhalt = dateien(&pfad);
+        assert_eq!(inhalt.len(), 1);
+        assert!(!inhalt[0].contains("streng-geheim"));
+        let gelesen = Mitschnitt::lesen(&inhalt[0]).unwrap();
+        assert_eq!(gelesen.kopf.user_id, Some(anna));
+        assert_eq!(gelesen.eintraege.len(), 3);
+        assert!(matches!(
+            gelesen.eintraege[1].nachricht.payload,
+            ControlPayload::Login(_)
+        ));
+        std::fs::remove_dir_all(pfad).unwrap();
+    }
+
+    #[test]
+    fn fremdes_format_wird_abgelehnt() {
+        assert!(matches!(
+            Mitschnitt::lesen("\n"),
+            Err(MitschnittFehler::Leer)
+        ));
+        let kopf = MitschnittKopf {
+            version: FORMAT_VERSION + 1,
+            ..MitschnittKopf::neu(None)
+        };
+        let text = serde_json::to_string(&kopf).unwrap();
+        assert!(matches!(
+            Mitschnitt::lesen(&text),
+            Err(MitschnittFehler::Version { .. })
+        ));
+        let text = format!(
+            "{}\n{{kaputt",
+            serde_json::to_string(&MitschnittKopf::neu(None)).unwrap()
+        );
+        assert!(matches!(
+            Mitschnitt::lesen(&text),
+            Err(MitschnittFehler::Format { zeile: 2, .. })
+        ));
+    }
+}
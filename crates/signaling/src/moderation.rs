@@ -0,0 +1,196 @@
+//! Moderation verbundener Clients ohne eigene Verbindung
+//!
+//! Der Commander (REST, TCP, gRPC) kickt, verschiebt und stupst Clients an,
+//! ohne selbst eine Signaling-Verbindung zu halten. Berechtigungen und
+//! Audit-Eintraege behandelt der Commander; hier geht es nur um den
+//! Live-Zustand. Ist der Client nicht verbunden, liefern alle Funktionen
+//! [`SignalingError::NichtGefunden`].
+
+use speakeasy_core::types::{ChannelId, UserId};
+use speakeasy_db::{
+    repository::UserRepository, BanRepository, ChannelRepository, ChatMessageRepository,
+    PermissionRepository, ServerGroupRepository,
+};
+use speakeasy_protocol::control::{ClientPokeRequest, ControlMessage, ControlPayload};
+use std::sync::Arc;
+
+use crate::error::{SignalingError, SignalingResult};
+use crate::handlers::client_handler::{client_trennen, client_verschieben, kick_meldung};
+use crate::handlers::voice_handler::sendemodus_neu_bewerten;
+use crate::server_state::SignalingState;
+
+/// Kickt einen Client vom Server
+///
+/// Der Client erhaelt den Grund, danach wird seine Verbindung geschlossen
+/// und Presence- sowie Voice-Zustand werden abgeraeumt.
+pub fn kicken<U, P, B>(
+    state: &Arc<SignalingState<U, P, B>>,
+    user_id: UserId,
+    grund: Option<&str>,
+) -> SignalingResult<()>
+where
+    U: UserRepository + ServerGroupRepository + ChannelRepository + ChatMessageRepository + 'static,
+    P: PermissionRepository + 'static,
+    B: BanRepository + 'static,
+{
+    online_pruefen(state, user_id)?;
+    let grund = grund.unwrap_or("Gekickt");
+    client_trennen(state, user_id, kick_meldung(grund));
+    tracing::info!(target = %user_id, grund = %grund, "Client vom Commander gekickt");
+    Ok(())
+}
+
+/// Verschiebt einen Client in einen anderen Kanal
+///
+/// Wie ein `ClientMove`: der Client erhaelt die Mitgliederliste, alle
+/// anderen ein `ClientMoved`.
+pub async fn verschieben<U, P, B>(
+    state: &Arc<SignalingState<U, P, B>>,
+    user_id: UserId,
+    channel_id: ChannelId,
+) -> SignalingResult<()>
+where
+    U: UserRepository + ServerGroupRepository + ChannelRepository + ChatMessageRepository + 'static,
+    P: PermissionRepository + 'static,
+    B: BanRepository + 'static,
+{
+    online_pruefen(state, user_id)?;
+    ChannelRepository::get_by_id(state.db.as_ref(), channel_id.inner())
+        .await
+        .map_err(|e| SignalingError::intern(e.to_string()))?
+        .ok_or_else(|| SignalingError::NichtGefunden(format!("Kanal {channel_id}")))?;
+
+    state.aktivitaet.melden(user_id);
+    client_verschieben(state, user_id, channel_id, None);
+    sendemodus_neu_bewerten(state, user_id, channel_id).await;
+    Ok(())
+}
+
+/// Stellt einem Client eine Poke-Nachricht zu
+pub fn anstupsen<U, P, B>(
+    state: &Arc<SignalingState<U, P, B>>,
+    user_id: UserId,
+    nachricht: String,
+) -> SignalingResult<()>
+where
+    U: UserRepository + ServerGroupRepository + ChannelRepository + ChatMessageRepository + 'static,
+    P: PermissionRepository + 'static,
+    B: BanRepository + 'static,
+{
+    online_pruefen(state, user_id)?;
+    let poke = ControlMessage::new(
+        0,
+        ControlPayload::ClientPoke(ClientPokeRequest {
+            target_user_id: user_id,
+            message: nachricht,
+        }),
+    );
+    state.broadcaster.an_user_senden(&user_id, poke);
+    Ok(())
+}
+
+fn online_pruefen<U, P, B>(
+    state: &Arc<SignalingState<U, P, B>>,
+    user_id: UserId,
+) -> SignalingResult<()>
+where
+    U: UserRepository + ServerGroupRepository + ChannelRepository + ChatMessageRepository + 'static,
+    P: PermissionRepository + 'static,
+    B: BanRepository + 'static,
+{
+    if state.presence.ist_online(&user_id) {
+        Ok(())
+    } else {
+        Err(SignalingError::NichtGefunden(format!(
+            "Client {user_id} nicht verbunden"
+        )))
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_hilfen::{neuer_client, test_state, TestState};
+    use speakeasy_db::models::NeuerKanal;
+    use speakeasy_protocol::control::ErrorCode;
+    use tokio::sync::mpsc;
+
+    fn verbinden(state: &TestState) -> (UserId, mpsc::Receiver<ControlMessage>) {
+        let user_id = neuer_client(state, None);
+        (user_id, state.broadcaster.client_registrieren(user_id))
+    }
+
+    #[tokio::test]
+    async fn kick_benachrichtigt_und_raeumt_ab() {
+        let state = test_state().await;
+        let (a, mut rx) = verbinden(&state);
+        state
+            .voice_state
+            .client_registrieren(a, 42, "127.0.0.1:40000".parse().unwrap());
+
+        kicken(&state, a, Some("Spam")).unwrap();
+
+        match rx.recv().await.unwrap().payload {
+            ControlPayload::Error(fehler) => {
+                assert_eq!(fehler.code, ErrorCode::InvalidRequest);
+                assert!(fehler.message.contains("Spam"));
+            }
+            andere => panic!("Kick-Meldung erwartet: {andere:?}"),
+        }
+        // Queue geschlossen: die Verbindung wird beendet
+        assert!(rx.recv().await.is_none());
+        assert!(!state.presence.ist_online(&a));
+        assert!(!state.voice_state.ssrc_belegt(42));
+    }
+
+    #[tokio::test]
+    async fn offline_ziel_ist_nicht_gefunden() {
+        let state = test_state().await;
+        let abwesend = UserId::new();
+
+        assert!(matches!(
+            kicken(&state, abwesend, None),
+            Err(SignalingError::NichtGefunden(_))
+        ));
+        assert!(matches!(
+            anstupsen(&state, abwesend, "Hallo".into()),
+            Err(SignalingError::NichtGefunden(_))
+        ));
+        assert!(matches!(
+            verschieben(&state, abwesend, ChannelId::new()).await,
+            Err(SignalingError::NichtGefunden(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn verschieben_und_anstupsen() {
+        let state = test_state().await;
+        let (a, mut rx) = verbinden(&state);
+        let kanal = ChannelRepository::create(
+            state.db.as_ref(),
+            NeuerKanal {
+                name: "Lobby",
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        let kanal = ChannelId(kanal.id);
+
+        verschieben(&state, a, kanal).await.unwrap();
+        assert_eq!(state.presence.channel_von_client(&a), Some(kanal));
+
+        anstupsen(&state, a, "Bitte melden".into()).unwrap();
+        let mut poke = None;
+        while let Ok(nachricht) = rx.try_recv() {
+            if let ControlPayload::ClientPoke(p) = nachricht.payload {
+                poke = Some(p.message);
+            }
+        }
+        assert_eq!(poke.as_deref(), Some("Bitte melden"));
+    }
+}
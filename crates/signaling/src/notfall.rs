@@ -0,0 +1,317 @@
+//! Notfall-Stummschaltung – Kanal per Klick serverseitig stumm schalten
+//!
+//! Den Zustand fuehrt die `NotfallStumm` des Voice-Crates (geteilt mit dem
+//! Router des Voice-Servers); dort werden die Pakete verworfen. Dieses Modul
+//! prueft die Berechtigungen, pflegt die Ausnahmen und verteilt
+//! `ChannelEmergencyMuteEvent`.
+//!
+//! Beide Berechtigungen zaehlen nur bei ausdruecklichem Grant – ohne Regel
+//! darf niemand stumm schalten und niemand ist ausgenommen:
+//! - [`NOTFALL_AUSLOESEN`] erlaubt das Ein- und Ausschalten im Kanal
+//! - [`NOTFALL_AUSNAHME`] nimmt ein Mitglied von der Stummschaltung aus
+//!
+//! Wer einem stumm geschalteten Kanal beitritt, wird beim Beitritt
+//! eingeordnet und erhaelt das Event.
+
+use speakeasy_core::types::{ChannelId, UserId};
+use speakeasy_db::{
+    models::{BerechtigungsWert, NeuerAuditEintrag, TriState},
+    repository::UserRepository,
+    AuditLogRepository, BanRepository, ChannelRepository, ChatMessageRepository,
+    PermissionRepository, ServerGroupRepository,
+};
+use speakeasy_protocol::control::{ChannelEmergencyMuteEvent, ControlMessage, ControlPayload};
+use std::sync::Arc;
+
+use crate::error::{SignalingError, SignalingResult};
+use crate::server_state::SignalingState;
+
+/// Berechtigung zum Ein- und Ausschalten der Notfall-Stummschaltung
+pub const NOTFALL_AUSLOESEN: &str = "b_channel_emergency_mute";
+/// Berechtigung, die von der Notfall-Stummschaltung ausnimmt
+pub const NOTFALL_AUSNAHME: &str = "b_channel_emergency_mute_bypass";
+
+/// Schaltet einen Kanal stumm oder hebt die Stummschaltung auf
+///
+/// Gemeinsamer Weg fuer den Control-Payload `ChannelEmergencyMute` und den
+/// Commander. Beim Einschalten greift die Stummschaltung sofort fuer alle
+/// Mitglieder; danach werden die Ausnahmen nachgetragen. So rutscht auch
+/// niemand durch, der waehrend der Pruefung beitritt. Das Event geht an den
+/// Kanal, der Audit-Eintrag nennt Akteur und Kanal.
+pub async fn kanal_notfall_stumm<U, P, B>(
+    state: &Arc<SignalingState<U, P, B>>,
+    actor_id: UserId,
+    channel_id: ChannelId,
+    aktivieren: bool,
+) -> SignalingResult<ChannelEmergencyMuteEvent>
+where
+    U: UserRepository
+        + ServerGroupRepository
+        + ChannelRepository
+        + ChatMessageRepository
+        + AuditLogRepository
+        + 'static,
+    P: PermissionRepository + 'static,
+    B: BanRepository + 'static,
+{
+    if !ausdruecklich_gewaehrt(state, actor_id, channel_id, NOTFALL_AUSLOESEN).await {
+        return Err(SignalingError::ZugriffVerweigert(
+            "Keine Berechtigung fuer die Notfall-Stummschaltung".into(),
+        ));
+    }
+    ChannelRepository::get_by_id(state.db.as_ref(), channel_id.inner())
+        .await
+        .map_err(|e| SignalingError::intern(e.to_string()))?
+        .ok_or_else(|| SignalingError::NichtGefunden(format!("Kanal {channel_id}")))?;
+
+    let notfall = state.channel_router.notfall();
+    let ausgenommen = if aktivieren {
+        notfall.aktivieren(channel_id, []);
+        for user_id in state.presence.user_ids_in_channel(&channel_id) {
+            if ausdruecklich_gewaehrt(state, user_id, channel_id, NOTFALL_AUSNAHME).await {
+                notfall.ausnahme_setzen(&channel_id, user_id, true);
+            }
+        }
+        notfall.ausgenommen(&channel_id).unwrap_or_default()
+    } else {
+        notfall.deaktivieren(&channel_id);
+        Vec::new()
+    };
+
+    let event = ChannelEmergencyMuteEvent {
+        channel_id,
+        active: aktivieren,
+        actor_id: Some(actor_id),
+        exempt: ausgenommen,
+    };
+    state.broadcaster.an_channel_senden(
+        &channel_id,
+        ControlMessage::new(0, ControlPayload::ChannelEmergencyMuteEvent(event.clone())),
+    );
+
+    tracing::warn!(
+        actor = %actor_id,
+        channel_id = %channel_id,
+        aktiv = aktivieren,
+        ausgenommen = event.exempt.len(),
+        "Notfall-Stummschaltung geaendert"
+    );
+
+    let aktion = if aktivieren {
+        "kanal.notfall_stumm"
+    } else {
+        "kanal.notfall_stumm_aufgehoben"
+    };
+    state
+        .audit_protokollieren(NeuerAuditEintrag::neu(
+            Some(actor_id.inner()),
+            aktion,
+            Some("channel"),
+            Some(&channel_id.to_string()),
+            serde_json::json!({ "ausgenommen": event.exempt }),
+        ))
+        .await;
+
+    Ok(event)
+}
+
+/// Ordnet einen Client nach dem Beitritt in einen stumm geschalteten Kanal ein
+///
+/// Ohne aktive Stummschaltung passiert nichts. Sonst wird die Ausnahme neu
+/// bewertet (Berechtigungen gelten pro Kanal) und der Client erhaelt das
+/// Event, damit er Banner und Mikrofon-Sperre anzeigt.
+pub(crate) async fn beim_beitritt<U, P, B>(
+    state: &SignalingState<U, P, B>,
+    user_id: UserId,
+    channel_id: ChannelId,
+) where
+    U: UserRepository + ServerGroupRepository + ChannelRepository + ChatMessageRepository + 'static,
+    P: PermissionRepository + 'static,
+    B: BanRepository + 'static,
+{
+    let notfall = state.channel_router.notfall();
+    if !notfall.ist_aktiv(&channel_id) {
+        return;
+    }
+    let ausgenommen = ausdruecklich_gewaehrt(state, user_id, channel_id, NOTFALL_AUSNAHME).await;
+    notfall.ausnahme_setzen(&channel_id, user_id, ausgenommen);
+
+    // Zwischen Pruefung und Meldung aufgehoben: Client sieht den Kanal frei
+    let Some(exempt) = notfall.ausgenommen(&channel_id) else {
+        return;
+    };
+    state.broadcaster.an_user_senden(
+        &user_id,
+        ControlMessage::new(
+            0,
+            ControlPayload::ChannelEmergencyMuteEvent(ChannelEmergencyMuteEvent {
+                channel_id,
+                active: true,
+                actor_id: None,
+                exempt,
+            }),
+        ),
+    );
+}
+
+/// Prueft ob eine Berechtigung im Kanal ausdruecklich gewaehrt ist
+///
+/// Anders als bei `berechtigung_pruefen` reicht eine fehlende Regel nicht.
+/// Bei Fehlern gilt die Berechtigung als nicht gewaehrt.
+pub(crate) async fn ausdruecklich_gewaehrt<U, P, B>(
+    state: &SignalingState<U, P, B>,
+    user_id: UserId,
+    channel_id: ChannelId,
+    key: &str,
+) -> bool
+where
+    U: UserRepository + ServerGroupRepository + ChannelRepository + ChatMessageRepository + 'static,
+    P: PermissionRepository + 'static,
+    B: BanRepository + 'static,
+{
+    match state
+        .permission_service
+        .alle_berechtigungen_holen(user_id.inner(), channel_id.inner())
+        .await
+    {
+        Ok(perms) => matches!(
+            perms.get(key),
+            Some(BerechtigungsWert::TriState(TriState::Grant))
+        ),
+        Err(e) => {
+            tracing::warn!(user_id = %user_id, key, fehler = %e, "Berechtigung nicht pruefbar");
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_hilfen::{client_anmelden, moderator, test_state, TestState};
+    use speakeasy_db::models::{AuditLogFilter, BerechtigungsZiel, NeuerKanal};
+
+    async fn kanal(state: &TestState) -> ChannelId {
+        ChannelRepository::create(
+            state.db.as_ref(),
+            NeuerKanal {
+                name: "Lobby",
+                ..Default::default()
+            },
+        )
+        .await
+        .map(|k| ChannelId(k.id))
+        .unwrap()
+    }
+
+    fn mitglied_anmelden(state: &TestState, user_id: UserId, kanal: ChannelId) {
+        client_anmelden(state, user_id, Some(kanal));
+        state.broadcaster.channel_beitreten(user_id, kanal);
+    }
+
+    async fn moderator_anmelden(state: &TestState, kanal: ChannelId) -> UserId {
+        let user_id = moderator(state).await;
+        mitglied_anmelden(state, user_id, kanal);
+        for key in [NOTFALL_AUSLOESEN, NOTFALL_AUSNAHME] {
+            gewaehren(state, user_id, kanal, key).await;
+        }
+        user_id
+    }
+
+    async fn gewaehren(state: &TestState, user_id: UserId, kanal: ChannelId, key: &str) {
+        state
+            .db
+            .set_permission(
+                &BerechtigungsZiel::Benutzer(user_id.inner()),
+                key,
+                BerechtigungsWert::TriState(TriState::Grant),
+                Some(kanal.inner()),
+            )
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn ohne_ausdrueckliches_grant_verweigert() {
+        let state = test_state().await;
+        let lobby = kanal(&state).await;
+        let gast = UserId::new();
+        mitglied_anmelden(&state, gast, lobby);
+
+        let err = kanal_notfall_stumm(&state, gast, lobby, true)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, SignalingError::ZugriffVerweigert(_)));
+        assert!(!state.channel_router.notfall().ist_aktiv(&lobby));
+    }
+
+    #[tokio::test]
+    async fn aktivieren_meldet_und_protokolliert() {
+        let state = test_state().await;
+        let lobby = kanal(&state).await;
+        let moderator = moderator_anmelden(&state, lobby).await;
+        let gast = UserId::new();
+        mitglied_anmelden(&state, gast, lobby);
+        let mut rx = state.broadcaster.client_registrieren(gast);
+
+        let event = kanal_notfall_stumm(&state, moderator, lobby, true)
+            .await
+            .unwrap();
+        assert_eq!(event.exempt, vec![moderator]);
+        let notfall = state.channel_router.notfall();
+        assert!(notfall.unterdrueckt(&lobby, &gast));
+        assert!(!notfall.unterdrueckt(&lobby, &moderator));
+        match rx.try_recv().unwrap().payload {
+            ControlPayload::ChannelEmergencyMuteEvent(ev) => {
+                assert!(ev.active);
+                assert_eq!(ev.actor_id, Some(moderator));
+            }
+            andere => panic!("unerwartet: {andere:?}"),
+        }
+
+        kanal_notfall_stumm(&state, moderator, lobby, false)
+            .await
+            .unwrap();
+        assert!(!notfall.unterdrueckt(&lobby, &gast));
+
+        let eintraege = state
+            .db
+            .list_events(AuditLogFilter {
+                actor_id: Some(moderator.inner()),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        let aktionen: Vec<&str> = eintraege.iter().map(|e| e.action.as_str()).collect();
+        assert!(aktionen.contains(&"kanal.notfall_stumm"));
+        assert!(aktionen.contains(&"kanal.notfall_stumm_aufgehoben"));
+        assert!(eintraege
+            .iter()
+            .all(|e| e.target_id.as_deref() == Some(lobby.to_string().as_str())));
+    }
+
+    #[tokio::test]
+    async fn beitretende_uebernehmen_den_zustand() {
+        let state = test_state().await;
+        let lobby = kanal(&state).await;
+        let moderator = moderator_anmelden(&state, lobby).await;
+        kanal_notfall_stumm(&state, moderator, lobby, true)
+            .await
+            .unwrap();
+
+        let neu = UserId::new();
+        mitglied_anmelden(&state, neu, lobby);
+        let mut rx = state.broadcaster.client_registrieren(neu);
+        beim_beitritt(&state, neu, lobby).await;
+
+        assert!(state.channel_router.notfall().unterdrueckt(&lobby, &neu));
+        match rx.try_recv().unwrap().payload {
+            ControlPayload::ChannelEmergencyMuteEvent(ev) => {
+                assert!(ev.active);
+                assert_eq!(ev.actor_id, None);
+                assert_eq!(ev.exempt, vec![moderator]);
+            }
+            andere => panic!("unerwartet: {andere:?}"),
+        }
+    }
+}
@@ -3,12 +3,27 @@
 //! Wer ist online, in welchem Channel? Dieser Manager haelt den ephemeren
 //! Zustand aller verbundenen Clients und benachrichtigt Subscriber bei
 //! Aenderungen (Join/Leave/StatusChange).
+//!
+//! Mit [`PresenceManager::mit_broadcaster`] erfahren auch die Clients davon:
+//! An- und Abmeldungen sowie Kanal-Beitritte und -Austritte gehen als
+//! versionierte `ClientConnected`/`ClientDisconnected`/
+//! `ClientJoinedChannel`/`ClientLeftChannel` an alle ausser dem
+//! betroffenen Client. Sie werden noch in der aendernden Methode
+//! versendet; ein Beitritt mit sofortiger Trennung kommt daher in dieser
+//! Reihenfolge an. Verschiebungen in einen anderen Kanal melden die
+//! Handler selbst als `ClientMoved`.
 
 use dashmap::DashMap;
 use speakeasy_core::types::{ChannelId, UserId};
+use speakeasy_protocol::control::{
+    ClientConnectedEvent, ClientDisconnectedEvent, ClientInfo, ClientJoinedChannelEvent,
+    ClientLeftChannelEvent, ControlMessage, ControlPayload,
+};
 use std::sync::Arc;
 use tokio::sync::broadcast;
 
+use crate::broadcast::EventBroadcaster;
+
 // ---------------------------------------------------------------------------
 // Presence-Events
 // ---------------------------------------------------------------------------
@@ -18,8 +33,11 @@ use tokio::sync::broadcast;
 pub enum PresenceEvent {
     /// Client hat sich verbunden
     ClientVerbunden { user_id: UserId, username: String },
-    /// Client hat sich getrennt
-    ClientGetrennt { user_id: UserId },
+    /// Client hat sich getrennt (mit dem Channel, in dem er zuletzt war)
+    ClientGetrennt {
+        user_id: UserId,
+        channel_id: Option<ChannelId>,
+    },
     /// Client ist einem Channel beigetreten
     ChannelBeigetreten {
         user_id: UserId,
@@ -43,10 +61,7 @@ pub enum PresenceEvent {
         is_output_muted: bool,
     },
     /// Nickname-Aenderung
-    NicknameGeaendert {
-        user_id: UserId,
-        nickname: String,
-    },
+    NicknameGeaendert { user_id: UserId, nickname: String },
     /// Away-Status geaendert
     AwayGeaendert {
         user_id: UserId,
@@ -70,6 +85,31 @@ pub struct ClientPresence {
     pub is_output_muted: bool,
     pub is_away: bool,
     pub away_message: Option<String>,
+    /// Nur-Zuhoeren: der Client empfaengt Sprache, sendet aber nicht
+    pub nur_hoeren: bool,
+    /// Vom Client gewuenschtes Nur-Zuhoeren (ohne Kanal-Richtlinie)
+    pub nur_hoeren_gewuenscht: bool,
+    /// IP-Adresse der Control-Verbindung (fuer IP-Bans)
+    pub ip: Option<String>,
+}
+
+impl ClientPresence {
+    /// Protokoll-Darstellung (Server-Gruppen werden nicht mitgefuehrt)
+    pub fn client_info(&self, ssrc: Option<u32>) -> ClientInfo {
+        ClientInfo {
+            user_id: self.user_id,
+            username: self.username.clone(),
+            display_name: self.display_name.clone(),
+            channel_id: self.channel_id,
+            server_groups: vec![],
+            is_muted: self.is_output_muted,
+            is_deafened: self.is_output_muted,
+            is_input_muted: self.is_input_muted,
+            ssrc,
+            listen_only: self.nur_hoeren,
+            soundboard: false,
+        }
+    }
 }
 
 // ---------------------------------------------------------------------------
@@ -94,17 +134,29 @@ struct PresenceManagerInner {
     channel_clients: DashMap<ChannelId, Vec<UserId>>,
     /// Broadcast-Sender fuer Presence-Events
     event_tx: broadcast::Sender<PresenceEvent>,
+    /// Verteilt Presence-Ereignisse an die Clients (ohne: nur `event_tx`)
+    broadcaster: Option<EventBroadcaster>,
 }
 
 impl PresenceManager {
     /// Erstellt einen neuen PresenceManager
     pub fn neu() -> Self {
+        Self::erstellen(None)
+    }
+
+    /// Erstellt einen PresenceManager, der Aenderungen an die Clients meldet
+    pub fn mit_broadcaster(broadcaster: EventBroadcaster) -> Self {
+        Self::erstellen(Some(broadcaster))
+    }
+
+    fn erstellen(broadcaster: Option<EventBroadcaster>) -> Self {
         let (event_tx, _) = broadcast::channel(EVENT_KANAL_GROESSE);
         Self {
             inner: Arc::new(PresenceManagerInner {
                 clients: DashMap::new(),
                 channel_clients: DashMap::new(),
                 event_tx,
+                broadcaster,
             }),
         }
     }
@@ -113,7 +165,14 @@ impl PresenceManager {
     pub fn client_verbunden(&self, presence: ClientPresence) {
         let user_id = presence.user_id;
         let username = presence.username.clone();
+        let client = presence.client_info(None);
         self.inner.clients.insert(user_id, presence);
+        self.melden(&user_id, |state_version| {
+            ControlPayload::ClientConnected(ClientConnectedEvent {
+                client,
+                state_version,
+            })
+        });
 
         tracing::info!(user_id = %user_id, username = %username, "Client online");
         let _ = self
@@ -126,23 +185,39 @@ impl PresenceManager {
     ///
     /// Entfernt den Client auch aus seinem Channel falls vorhanden.
     pub fn client_getrennt(&self, user_id: &UserId) {
-        if let Some((_, presence)) = self.inner.clients.remove(user_id) {
+        if let Some((_, mut presence)) = self.inner.clients.remove(user_id) {
             // Aus Channel entfernen falls vorhanden
-            if let Some(channel_id) = presence.channel_id {
+            let letzter_channel = presence.channel_id.take();
+            if let Some(channel_id) = letzter_channel {
                 self.aus_channel_entfernen_intern(user_id, &channel_id);
+                let client = presence.client_info(None);
+                self.melden(user_id, |state_version| {
+                    ControlPayload::ClientLeftChannel(ClientLeftChannelEvent {
+                        client,
+                        channel_id,
+                        state_version,
+                    })
+                });
             }
+            let client = presence.client_info(None);
+            self.melden(user_id, |state_version| {
+                ControlPayload::ClientDisconnected(ClientDisconnectedEvent {
+                    client,
+                    state_version,
+                })
+            });
 
             tracing::info!(user_id = %user_id, "Client offline");
-            let _ = self
-                .inner
-                .event_tx
-                .send(PresenceEvent::ClientGetrennt { user_id: *user_id });
+            let _ = self.inner.event_tx.send(PresenceEvent::ClientGetrennt {
+                user_id: *user_id,
+                channel_id: letzter_channel,
+            });
         }
     }
 
     /// Fuegt einen Client einem Channel hinzu
     pub fn channel_beitreten(&self, user_id: UserId, channel_id: ChannelId) {
-        let alter_channel = {
+        let (alter_channel, client) = {
             let mut entry = match self.inner.clients.get_mut(&user_id) {
                 Some(e) => e,
                 None => {
@@ -152,7 +227,7 @@ impl PresenceManager {
             };
             let alter = entry.channel_id;
             entry.channel_id = Some(channel_id);
-            alter
+            (alter, entry.client_info(None))
         };
 
         // Aus altem Channel entfernen
@@ -170,6 +245,13 @@ impl PresenceManager {
                 user_id,
                 channel_id,
             });
+            self.melden(&user_id, |state_version| {
+                ControlPayload::ClientJoinedChannel(ClientJoinedChannelEvent {
+                    client,
+                    channel_id,
+                    state_version,
+                })
+            });
         }
 
         // Zum neuen Channel hinzufuegen
@@ -184,14 +266,14 @@ impl PresenceManager {
 
     /// Entfernt einen Client aus seinem Channel
     pub fn channel_verlassen(&self, user_id: &UserId) {
-        let channel_id = {
+        let (channel_id, client) = {
             let mut entry = match self.inner.clients.get_mut(user_id) {
                 Some(e) => e,
                 None => return,
             };
             let c = entry.channel_id;
             entry.channel_id = None;
-            c
+            (c, entry.client_info(None))
         };
 
         if let Some(channel_id) = channel_id {
@@ -200,6 +282,13 @@ impl PresenceManager {
                 user_id: *user_id,
                 channel_id,
             });
+            self.melden(user_id, |state_version| {
+                ControlPayload::ClientLeftChannel(ClientLeftChannelEvent {
+                    client,
+                    channel_id,
+                    state_version,
+                })
+            });
             tracing::debug!(user_id = %user_id, channel_id = %channel_id, "Client Channel verlassen");
         }
     }
@@ -282,10 +371,22 @@ impl PresenceManager {
             entry.is_away = away;
             entry.away_message = message.clone();
         }
-        let _ = self
-            .inner
-            .event_tx
-            .send(PresenceEvent::AwayGeaendert { user_id, away, message });
+        let _ = self.inner.event_tx.send(PresenceEvent::AwayGeaendert {
+            user_id,
+            away,
+            message,
+        });
+    }
+
+    /// Setzt den Sendemodus eines Clients
+    ///
+    /// `gewuenscht` ist die Wahl des Clients, `nur_hoeren` der effektive
+    /// Modus nach Anwendung der Kanal-Richtlinie.
+    pub fn sendemodus_setzen(&self, user_id: &UserId, gewuenscht: bool, nur_hoeren: bool) {
+        if let Some(mut entry) = self.inner.clients.get_mut(user_id) {
+            entry.nur_hoeren_gewuenscht = gewuenscht;
+            entry.nur_hoeren = nur_hoeren;
+        }
     }
 
     /// Gibt alle User-IDs in einem Channel zurueck
@@ -306,6 +407,18 @@ impl PresenceManager {
     // Interne Hilfsmethoden
     // -----------------------------------------------------------------------
 
+    /// Sendet ein Presence-Ereignis an alle Clients ausser `betroffen`
+    ///
+    /// Die SSRC fehlt in den mitgesendeten `ClientInfo`s; sie kommt ueber
+    /// `ClientVoiceUpdated`.
+    fn melden(&self, betroffen: &UserId, bauen: impl FnOnce(u64) -> ControlPayload) {
+        if let Some(broadcaster) = &self.inner.broadcaster {
+            broadcaster.zustand_senden_ausser(betroffen, |state_version| {
+                ControlMessage::new(0, bauen(state_version))
+            });
+        }
+    }
+
     fn aus_channel_entfernen_intern(&self, user_id: &UserId, channel_id: &ChannelId) {
         if let Some(mut ids) = self.inner.channel_clients.get_mut(channel_id) {
             ids.retain(|uid| uid != user_id);
@@ -331,6 +444,7 @@ impl Default for PresenceManager {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use speakeasy_protocol::conformance::variante_name;
 
     fn test_presence(user_id: UserId, name: &str) -> ClientPresence {
         ClientPresence {
@@ -342,6 +456,9 @@ mod tests {
             is_output_muted: false,
             is_away: false,
             away_message: None,
+            nur_hoeren: false,
+            nur_hoeren_gewuenscht: false,
+            ip: None,
         }
     }
 
@@ -430,4 +547,71 @@ mod tests {
         let event = rx.try_recv().expect("Event muss vorhanden sein");
         assert!(matches!(event, PresenceEvent::ClientVerbunden { .. }));
     }
+
+    fn empfangen(rx: &mut tokio::sync::mpsc::Receiver<ControlMessage>) -> Vec<ControlPayload> {
+        std::iter::from_fn(|| rx.try_recv().ok())
+            .map(|m| m.payload)
+            .collect()
+    }
+
+    #[test]
+    fn beitritt_und_trennung_werden_in_reihenfolge_gemeldet() {
+        let broadcaster = EventBroadcaster::neu();
+        let pm = PresenceManager::mit_broadcaster(broadcaster.clone());
+        let (anna, bert) = (UserId::new(), UserId::new());
+        let kanal = ChannelId::new();
+        let mut anna_rx = broadcaster.client_registrieren(anna);
+        let mut bert_rx = broadcaster.client_registrieren(bert);
+        let vorher = broadcaster.zustand_version();
+
+        pm.client_verbunden(test_presence(anna, "anna"));
+        pm.channel_beitreten(anna, kanal);
+        pm.client_getrennt(&anna);
+
+        let ereignisse = empfangen(&mut bert_rx);
+        let namen: Vec<_> = ereignisse.iter().map(variante_name).collect();
+        assert_eq!(
+            namen,
+            [
+                "client_connected",
+                "client_joined_channel",
+                "client_left_channel",
+                "client_disconnected"
+            ]
+        );
+        if let ControlPayload::ClientJoinedChannel(ev) = &ereignisse[1] {
+            assert_eq!(ev.channel_id, kanal);
+            assert_eq!(ev.client.channel_id, Some(kanal));
+        }
+        if let ControlPayload::ClientDisconnected(ev) = &ereignisse[3] {
+            assert_eq!(ev.client.user_id, anna);
+            assert_eq!(ev.client.channel_id, None);
+        }
+        // Der Ausloeser erfaehrt es aus seinen Antworten
+        assert!(empfangen(&mut anna_rx).is_empty());
+        assert_eq!(broadcaster.diff_seit(vorher).events.len(), 4);
+    }
+
+    #[test]
+    fn verlassen_und_wechsel() {
+        let broadcaster = EventBroadcaster::neu();
+        let pm = PresenceManager::mit_broadcaster(broadcaster.clone());
+        let (anna, bert) = (UserId::new(), UserId::new());
+        let (kanal_a, kanal_b) = (ChannelId::new(), ChannelId::new());
+        pm.client_verbunden(test_presence(anna, "anna"));
+        pm.channel_beitreten(anna, kanal_a);
+        let mut bert_rx = broadcaster.client_registrieren(bert);
+
+        pm.channel_verlassen(&anna);
+        pm.channel_verlassen(&anna);
+        assert!(matches!(
+            &empfangen(&mut bert_rx)[..],
+            [ControlPayload::ClientLeftChannel(ev)] if ev.channel_id == kanal_a
+        ));
+
+        // Verschiebungen melden die Handler als ClientMoved
+        pm.channel_beitreten(anna, kanal_a);
+        pm.channel_beitreten(anna, kanal_b);
+        assert_eq!(empfangen(&mut bert_rx).len(), 1);
+    }
 }
@@ -0,0 +1,390 @@
+//! E2E-Schluesselrotation – neue Gruppenschluessel bei Mitgliederwechseln
+//!
+//! Im E2E-Modus kennt der Server die Gruppenschluessel nicht. Er fuehrt nur
+//! pro Kanal die aktuelle Epoch und den Schluesselverteiler: das am laengsten
+//! anwesende Mitglied, das beim `VoiceInit` einen oeffentlichen X25519-
+//! Schluessel gemeldet hat. Aendern sich die Mitglieder (Beitritt, Austritt,
+//! Verschieben, Trennen, Voice-Abbau), erhoeht der Server die Epoch und
+//! schickt dem Verteiler ein `E2EKeyRotationRequired` mit allen Empfaengern.
+//! Dessen `GroupKeyDistribute` leitet der `e2e_handler` weiter.
+//!
+//! Die Mitgliederwechsel kommen als Presence-Events; ein Hintergrund-Task
+//! arbeitet sie ab, solange der Server im E2E-Modus laeuft.
+
+use dashmap::DashMap;
+use speakeasy_core::types::{ChannelId, UserId};
+use speakeasy_db::{
+    repository::UserRepository, BanRepository, ChannelRepository, ChatMessageRepository,
+    PermissionRepository, ServerGroupRepository,
+};
+use speakeasy_protocol::control::{
+    ControlMessage, ControlPayload, E2EKeyRotationRequiredEvent, E2EMemberKey,
+};
+use speakeasy_protocol::crypto::{CryptoMode, KeyRotationReason};
+use std::sync::Arc;
+use tokio::sync::broadcast::error::RecvError;
+
+use crate::presence::PresenceEvent;
+use crate::server_state::SignalingState;
+
+/// Epoch und Verteiler eines Kanals
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KanalEpoche {
+    pub epoch: u32,
+    pub verteiler: UserId,
+}
+
+/// Oeffentliche Schluessel der Clients und Epoch je Kanal
+#[derive(Default)]
+pub struct E2ESchluessel {
+    /// Gemeldete X25519-Schluessel (Base64, geprueft)
+    oeffentlich: DashMap<UserId, String>,
+    /// Kanaele mit mindestens einem Empfaenger
+    kanaele: DashMap<ChannelId, KanalEpoche>,
+}
+
+impl E2ESchluessel {
+    pub fn neu() -> Self {
+        Self::default()
+    }
+
+    /// Merkt sich den oeffentlichen Schluessel eines Clients
+    ///
+    /// Gibt `true` zurueck, wenn er neu oder geaendert ist.
+    pub fn schluessel_setzen(&self, user_id: UserId, public_key: String) -> bool {
+        self.oeffentlich
+            .insert(user_id, public_key.clone())
+            .as_ref()
+            != Some(&public_key)
+    }
+
+    /// Vergisst den Schluessel eines Clients (Voice-Abbau, Trennung)
+    ///
+    /// Gibt `true` zurueck, wenn einer gemeldet war.
+    pub fn schluessel_entfernen(&self, user_id: &UserId) -> bool {
+        self.oeffentlich.remove(user_id).is_some()
+    }
+
+    /// Gemeldeter Schluessel eines Clients
+    pub fn schluessel_von(&self, user_id: &UserId) -> Option<String> {
+        self.oeffentlich.get(user_id).map(|k| k.clone())
+    }
+
+    /// Aktuelle Epoch und Verteiler eines Kanals
+    pub fn kanal(&self, channel_id: &ChannelId) -> Option<KanalEpoche> {
+        self.kanaele.get(channel_id).map(|k| *k)
+    }
+
+    /// Beginnt eine neue Epoch fuer die Mitglieder eines Kanals
+    ///
+    /// `mitglieder` in Beitrittsreihenfolge; Verteiler wird das erste mit
+    /// gemeldetem Schluessel. Ohne solches Mitglied wird der Kanal vergessen
+    /// und `None` zurueckgegeben.
+    pub fn rotieren(
+        &self,
+        channel_id: ChannelId,
+        mitglieder: &[UserId],
+        reason: KeyRotationReason,
+    ) -> Option<(UserId, E2EKeyRotationRequiredEvent)> {
+        let members: Vec<E2EMemberKey> = mitglieder
+            .iter()
+            .filter_map(|user_id| {
+                self.schluessel_von(user_id).map(|public_key| E2EMemberKey {
+                    user_id: *user_id,
+                    public_key,
+                })
+            })
+            .collect();
+        let Some(verteiler) = members.first().map(|m| m.user_id) else {
+            self.kanaele.remove(&channel_id);
+            return None;
+        };
+
+        let epoch = match self.kanaele.get(&channel_id) {
+            Some(k) => k.epoch.wrapping_add(1),
+            None => 0,
+        };
+        self.kanaele
+            .insert(channel_id, KanalEpoche { epoch, verteiler });
+        Some((
+            verteiler,
+            E2EKeyRotationRequiredEvent {
+                channel_id,
+                epoch,
+                reason,
+                members,
+            },
+        ))
+    }
+}
+
+/// Prueft, ob der Server im E2E-Modus laeuft
+pub fn e2e_aktiv<U, P, B>(state: &SignalingState<U, P, B>) -> bool
+where
+    U: UserRepository + ServerGroupRepository + ChannelRepository + ChatMessageRepository + 'static,
+    P: PermissionRepository + 'static,
+    B: BanRepository + 'static,
+{
+    state.config.crypto_mode.parse::<CryptoMode>() == Ok(CryptoMode::E2E)
+}
+
+/// Stoesst eine Rotation fuer einen Kanal an
+///
+/// Gibt den benachrichtigten Verteiler zurueck (`None` ausserhalb des
+/// E2E-Modus oder ohne Empfaenger im Kanal).
+pub fn rotation_anstossen<U, P, B>(
+    state: &SignalingState<U, P, B>,
+    channel_id: ChannelId,
+    reason: KeyRotationReason,
+) -> Option<UserId>
+where
+    U: UserRepository + ServerGroupRepository + ChannelRepository + ChatMessageRepository + 'static,
+    P: PermissionRepository + 'static,
+    B: BanRepository + 'static,
+{
+    if !e2e_aktiv(state) {
+        return None;
+    }
+    let mitglieder = state.presence.user_ids_in_channel(&channel_id);
+    let (verteiler, ereignis) = state
+        .e2e
+        .rotieren(channel_id, &mitglieder, reason.clone())?;
+
+    tracing::debug!(
+        channel_id = %channel_id,
+        epoch = ereignis.epoch,
+        verteiler = %verteiler,
+        empfaenger = ereignis.members.len(),
+        grund = ?reason,
+        "E2E-Schluesselrotation angefordert"
+    );
+    state.broadcaster.an_user_senden(
+        &verteiler,
+        ControlMessage::new(0, ControlPayload::E2EKeyRotationRequired(ereignis)),
+    );
+    Some(verteiler)
+}
+
+/// Setzt ein Presence-Event in Rotationen um
+pub fn ereignis_verarbeiten<U, P, B>(state: &SignalingState<U, P, B>, ereignis: PresenceEvent)
+where
+    U: UserRepository + ServerGroupRepository + ChannelRepository + ChatMessageRepository + 'static,
+    P: PermissionRepository + 'static,
+    B: BanRepository + 'static,
+{
+    match ereignis {
+        // Ohne Schluessel kann der Neue ohnehin nichts empfangen; er loest
+        // die Rotation mit seinem VoiceInit aus
+        PresenceEvent::ChannelBeigetreten {
+            user_id,
+            channel_id,
+        } if state.e2e.schluessel_von(&user_id).is_some() => {
+            rotation_anstossen(state, channel_id, KeyRotationReason::MemberJoined);
+        }
+        PresenceEvent::ChannelVerlassen { channel_id, .. } => {
+            rotation_anstossen(state, channel_id, KeyRotationReason::MemberLeft);
+        }
+        PresenceEvent::ClientVerschoben {
+            user_id,
+            von_channel,
+            zu_channel,
+        } => {
+            if let Some(von) = von_channel {
+                rotation_anstossen(state, von, KeyRotationReason::MemberLeft);
+            }
+            if state.e2e.schluessel_von(&user_id).is_some() {
+                rotation_anstossen(state, zu_channel, KeyRotationReason::MemberJoined);
+            }
+        }
+        PresenceEvent::ClientGetrennt {
+            user_id,
+            channel_id,
+        } => {
+            state.e2e.schluessel_entfernen(&user_id);
+            if let Some(channel_id) = channel_id {
+                rotation_anstossen(state, channel_id, KeyRotationReason::MemberLeft);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Hintergrund-Task: rotiert bei Mitgliederwechseln bis zum Shutdown
+///
+/// Endet sofort, wenn der Server nicht im E2E-Modus laeuft.
+pub async fn schluesselrotation_loop<U, P, B>(
+    state: Arc<SignalingState<U, P, B>>,
+    mut shutdown_rx: tokio::sync::watch::Receiver<bool>,
+) where
+    U: UserRepository + ServerGroupRepository + ChannelRepository + ChatMessageRepository + 'static,
+    P: PermissionRepository + 'static,
+    B: BanRepository + 'static,
+{
+    if !e2e_aktiv(&state) {
+        return;
+    }
+    let mut ereignisse = state.presence.events_abonnieren();
+
+    loop {
+        tokio::select! {
+            ereignis = ereignisse.recv() => match ereignis {
+                Ok(ereignis) => ereignis_verarbeiten(&state, ereignis),
+                Err(RecvError::Lagged(verpasst)) => {
+                    // Welche Kanaele betroffen sind, ist unbekannt: alle rotieren
+                    tracing::warn!(verpasst, "Presence-Events verpasst, rotiere alle E2E-Kanaele");
+                    let kanaele: Vec<ChannelId> =
+                        state.e2e.kanaele.iter().map(|k| *k.key()).collect();
+                    for channel_id in kanaele {
+                        rotation_anstossen(&state, channel_id, KeyRotationReason::Scheduled);
+                    }
+                }
+                Err(RecvError::Closed) => break,
+            },
+            Ok(()) = shutdown_rx.changed() => {
+                if *shutdown_rx.borrow() {
+                    break;
+                }
+            }
+        }
+    }
+    tracing::debug!("E2E-Schluesselrotation beendet");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_hilfen::{client_anmelden, TestAufbau, TestState};
+    use tokio::sync::mpsc;
+
+    const SCHLUESSEL: &str = "AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA=";
+
+    async fn state(crypto_mode: &str) -> TestState {
+        TestAufbau::default().crypto_mode(crypto_mode).bauen().await
+    }
+
+    fn mitglied(
+        state: &TestState,
+        kanal: ChannelId,
+        mit_schluessel: bool,
+    ) -> (UserId, mpsc::Receiver<ControlMessage>) {
+        let user_id = UserId::new();
+        let rx = state.broadcaster.client_registrieren(user_id);
+        client_anmelden(state, user_id, None);
+        if mit_schluessel {
+            state.e2e.schluessel_setzen(user_id, SCHLUESSEL.into());
+        }
+        state.presence.channel_beitreten(user_id, kanal);
+        (user_id, rx)
+    }
+
+    fn rotationen(rx: &mut mpsc::Receiver<ControlMessage>) -> Vec<E2EKeyRotationRequiredEvent> {
+        std::iter::from_fn(|| rx.try_recv().ok())
+            .filter_map(|m| match m.payload {
+                ControlPayload::E2EKeyRotationRequired(ev) => Some(ev),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn erstes_mitglied_mit_schluessel_verteilt() {
+        let state = state("e2e").await;
+        let kanal = ChannelId::new();
+        let (ohne, mut ohne_rx) = mitglied(&state, kanal, false);
+        let (anna, mut anna_rx) = mitglied(&state, kanal, true);
+        let (bert, mut bert_rx) = mitglied(&state, kanal, true);
+
+        assert_eq!(
+            rotation_anstossen(&state, kanal, KeyRotationReason::MemberJoined),
+            Some(anna)
+        );
+        let ereignisse = rotationen(&mut anna_rx);
+        assert_eq!(ereignisse.len(), 1);
+        assert_eq!(ereignisse[0].epoch, 0);
+        let empfaenger: Vec<_> = ereignisse[0].members.iter().map(|m| m.user_id).collect();
+        assert_eq!(empfaenger, [anna, bert]);
+        assert!(!empfaenger.contains(&ohne));
+        assert!(rotationen(&mut bert_rx).is_empty());
+        assert!(rotationen(&mut ohne_rx).is_empty());
+    }
+
+    #[tokio::test]
+    async fn austritt_des_verteilers_erhoeht_epoch_und_waehlt_neu() {
+        let state = state("e2e").await;
+        let kanal = ChannelId::new();
+        let (anna, _anna_rx) = mitglied(&state, kanal, true);
+        let (bert, mut bert_rx) = mitglied(&state, kanal, true);
+        rotation_anstossen(&state, kanal, KeyRotationReason::MemberJoined);
+
+        state.presence.channel_verlassen(&anna);
+        ereignis_verarbeiten(
+            &state,
+            PresenceEvent::ChannelVerlassen {
+                user_id: anna,
+                channel_id: kanal,
+            },
+        );
+
+        let ereignisse = rotationen(&mut bert_rx);
+        assert_eq!(ereignisse.len(), 1);
+        assert_eq!(ereignisse[0].epoch, 1);
+        assert_eq!(ereignisse[0].reason, KeyRotationReason::MemberLeft);
+        assert_eq!(
+            state.e2e.kanal(&kanal),
+            Some(KanalEpoche {
+                epoch: 1,
+                verteiler: bert
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn trennung_vergisst_schluessel_und_leeren_kanal() {
+        let state = state("e2e").await;
+        let kanal = ChannelId::new();
+        let (anna, _anna_rx) = mitglied(&state, kanal, true);
+        rotation_anstossen(&state, kanal, KeyRotationReason::MemberJoined);
+
+        state.presence.client_getrennt(&anna);
+        ereignis_verarbeiten(
+            &state,
+            PresenceEvent::ClientGetrennt {
+                user_id: anna,
+                channel_id: Some(kanal),
+            },
+        );
+        assert!(state.e2e.schluessel_von(&anna).is_none());
+        assert!(state.e2e.kanal(&kanal).is_none());
+    }
+
+    #[tokio::test]
+    async fn beitritt_ohne_schluessel_rotiert_nicht() {
+        let state = state("e2e").await;
+        let kanal = ChannelId::new();
+        let (_anna, mut anna_rx) = mitglied(&state, kanal, true);
+        let (bert, _bert_rx) = mitglied(&state, kanal, false);
+
+        ereignis_verarbeiten(
+            &state,
+            PresenceEvent::ChannelBeigetreten {
+                user_id: bert,
+                channel_id: kanal,
+            },
+        );
+        assert!(rotationen(&mut anna_rx).is_empty());
+    }
+
+    #[tokio::test]
+    async fn ohne_e2e_modus_keine_rotation() {
+        let state = state("dtls").await;
+        let kanal = ChannelId::new();
+        let (_anna, mut anna_rx) = mitglied(&state, kanal, true);
+
+        assert_eq!(
+            rotation_anstossen(&state, kanal, KeyRotationReason::MemberJoined),
+            None
+        );
+        assert!(rotationen(&mut anna_rx).is_empty());
+        assert!(state.e2e.kanal(&kanal).is_none());
+    }
+}
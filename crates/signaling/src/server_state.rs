@@ -3,19 +3,41 @@
 //! Haelt alle geteilten Services und Zustands-Manager als Arc-Referenzen,
 //! die sicher zwischen tokio-Tasks geteilt werden koennen.
 
+use serde::{Deserialize, Serialize};
 use speakeasy_auth::{AuthService, BanService, PermissionService};
-use speakeasy_chat::ChatService;
+use speakeasy_chat::{ChatService, DateiDienst, KontoDienst, UploadDienst};
 use speakeasy_core::types::ServerId;
 use speakeasy_db::{
-    repository::UserRepository, BanRepository, ChannelRepository, ChatMessageRepository,
+    audit_puffer::AuditSink,
+    einstellungen::ServerEinstellungen,
+    models::NeuerAuditEintrag,
+    motd::Motd,
+    repository::UserRepository,
+    zeitlimit::{ZeitlimitMetriken, Zeitlimits},
+    AuditLogRepository, BanRepository, ChannelRepository, ChatMessageRepository,
     PermissionRepository, ServerGroupRepository,
 };
-use speakeasy_voice::{ChannelRouter, VoiceState};
-use std::sync::Arc;
+use speakeasy_protocol::codec::AudioPreset;
+use speakeasy_protocol::wire::DEFAULT_MAX_FRAME_SIZE;
+use speakeasy_voice::{
+    AktivitaetsTracker, ChannelRouter, NotfallStumm, Soundboard, SprecherTracker, VoiceState,
+};
+use std::sync::{Arc, OnceLock, RwLock};
 use std::time::Instant;
 
-use crate::broadcast::EventBroadcaster;
+use crate::afk::{AfkRichtlinie, AfkWaechter};
+use crate::anfragelimit::{AnfrageBegrenzer, AnfrageLimits};
+use crate::broadcast::{EventBroadcaster, ReplayKonfig};
+use crate::drosselung::DrosselLimits;
+use crate::einladung::{Einladungen, EinladungsLimits};
+use crate::kanalbaum::STANDARD_TEILWEISE_AB;
+use crate::langsammodus::Langsammodus;
+use crate::mitglieder::{STANDARD_TEILWEISE_AB as MITGLIEDER_TEILWEISE_AB, STANDARD_VORSCHAU};
+use crate::mitschnitt::{MitschnittKonfig, Mitschnitte};
 use crate::presence::PresenceManager;
+use crate::schluesselrotation::E2ESchluessel;
+use crate::trennung::AngekuendigteTrennungen;
+use crate::soundboard::{SoundQuelle, SoundboardLimits, SoundboardZustand};
 
 /// Konfiguration fuer den Signaling-Service
 #[derive(Debug, Clone)]
@@ -28,6 +50,13 @@ pub struct SignalingConfig {
     pub welcome_message: Option<String>,
     /// Maximale Clients
     pub max_clients: u32,
+    /// Hinweis des Betreibers (z.B. geplante Wartung)
+    pub host_message: Option<String>,
+    /// Standard-Frist fuer das Bearbeiten und Loeschen eigener
+    /// Chat-Nachrichten in Sekunden (0 = unbegrenzt)
+    pub chat_bearbeitungsfrist_sek: u32,
+    /// Nachricht des Tages beim Start (zur Laufzeit per Commander aenderbar)
+    pub motd: Motd,
     /// UDP-Port des Voice-Servers (fuer VoiceInit-Antworten)
     pub voice_udp_port: u16,
     /// Server-IP fuer Voice-Verbindungen
@@ -36,10 +65,99 @@ pub struct SignalingConfig {
     pub keepalive_sek: u64,
     /// Timeout fuer inaktive Verbindungen in Sekunden
     pub verbindungs_timeout_sek: u64,
+    /// Groesste angenommene Control-Nachricht in Bytes; auf groessere Frames
+    /// antwortet der Server mit einem Fehler und trennt
+    pub max_frame_bytes: usize,
+    /// `Login` und `Register` erst nach `Hello` (Clients vor Protokoll 1.26
+    /// senden keines)
+    pub hello_erforderlich: bool,
+    /// Restlaufzeit der Session in Sekunden, ab der der Server mit
+    /// `SessionExpiryWarning` warnt (0 = keine Warnung)
+    pub sitzungswarnung_sek: u64,
     /// Krypto-Modus fuer Voice ("none", "dtls", "e2e")
     pub crypto_mode: String,
     /// DTLS-Fingerprint des Servers (wenn TLS konfiguriert)
     pub dtls_fingerprint: Option<String>,
+    /// Start-Richtlinie der AFK-Erkennung (zur Laufzeit per ServerEdit aenderbar)
+    pub afk: AfkRichtlinie,
+    /// Zeitlimits fuer lesende und schreibende Handler-Aufrufe
+    pub zeitlimits: Zeitlimits,
+    /// Ab dieser Kanalanzahl wird der Kanalbaum nur teilweise ausgeliefert
+    /// (0 = immer vollstaendig)
+    pub kanalbaum_teilweise_ab: usize,
+    /// Ab mehr Mitgliedern enthaelt die Beitrittsantwort nur eine Auswahl
+    /// (0 = immer alle)
+    pub mitglieder_teilweise_ab: usize,
+    /// Anzahl zuletzt aktiver Mitglieder in einer gekuerzten Beitrittsantwort
+    pub mitglieder_vorschau: usize,
+    /// Clients ohne funktionierendes Opus duerfen PCMU aushandeln
+    pub pcm_fallback_erlaubt: bool,
+    /// Grenzen fuer die beim VoiceInit ausgehandelte Opus-Konfiguration
+    pub codec: CodecRichtlinie,
+    /// Grenzen fuer gleichzeitig laufende Anfragen
+    pub anfrage_limits: AnfrageLimits,
+    /// Rate-Begrenzung je Verbindung und Nachrichtenkategorie
+    pub drosselung: DrosselLimits,
+    /// Replay-Ring fuer verpasste Presence-Ereignisse (`StateDiff`)
+    pub replay: ReplayKonfig,
+    /// Grenzen fuer Soundboard-Wiedergaben je Benutzer und Kanal
+    pub soundboard: SoundboardLimits,
+    /// Grenzen fuer Kanal-Einladungen und Beitrittsanfragen
+    pub einladungen: EinladungsLimits,
+    /// Sitzungsmitschnitte zur Fehlersuche (Standard: aus)
+    pub mitschnitt: MitschnittKonfig,
+    /// Wer sich per `Register` ein Konto anlegen darf
+    pub registrierung: RegistrierungsModus,
+}
+
+/// Zulassung neuer Konten ueber `Register`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RegistrierungsModus {
+    /// Jeder darf sich registrieren; ein Einladungscode ist optional
+    #[serde(rename = "open")]
+    Offen,
+    /// Nur mit gueltigem Einladungscode
+    #[default]
+    #[serde(rename = "invite_only")]
+    NurEinladung,
+    /// Keine Registrierung, Konten legt nur ein Admin an
+    #[serde(rename = "closed")]
+    Geschlossen,
+}
+
+/// Server-Richtlinie fuer die Opus-Aushandlung
+///
+/// Verlangt ein Client mehr, wird seine Konfiguration herabgestuft statt
+/// abgelehnt (siehe `voice_handler::opus_aushandeln`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CodecRichtlinie {
+    /// Hoechste Bitrate je Client in kbps
+    pub max_bitrate_kbps: u16,
+    /// Zulaessige Presets (leer = alle)
+    pub erlaubte_presets: Vec<AudioPreset>,
+}
+
+impl CodecRichtlinie {
+    /// Zulaessige Presets, absteigend nach Bitrate
+    pub fn presets(&self) -> impl Iterator<Item = AudioPreset> + '_ {
+        AudioPreset::ALLE
+            .into_iter()
+            .filter(|p| self.erlaubte_presets.is_empty() || self.erlaubte_presets.contains(p))
+    }
+
+    /// Schraenkt die Richtlinie die Presets ein?
+    pub fn presets_eingeschraenkt(&self) -> bool {
+        AudioPreset::ALLE.len() != self.presets().count()
+    }
+}
+
+impl Default for CodecRichtlinie {
+    fn default() -> Self {
+        Self {
+            max_bitrate_kbps: 510,
+            erlaubte_presets: Vec::new(),
+        }
+    }
 }
 
 impl Default for SignalingConfig {
@@ -49,12 +167,106 @@ impl Default for SignalingConfig {
             server_name: "Speakeasy Server".to_string(),
             welcome_message: None,
             max_clients: 512,
+            host_message: None,
+            chat_bearbeitungsfrist_sek: 0,
+            motd: Motd::default(),
             voice_udp_port: 9987,
             voice_server_ip: "0.0.0.0".to_string(),
             keepalive_sek: 30,
             verbindungs_timeout_sek: 90,
+            max_frame_bytes: DEFAULT_MAX_FRAME_SIZE,
+            hello_erforderlich: true,
+            sitzungswarnung_sek: 10 * 60,
             crypto_mode: "none".to_string(),
             dtls_fingerprint: None,
+            afk: AfkRichtlinie::default(),
+            zeitlimits: Zeitlimits::default(),
+            kanalbaum_teilweise_ab: STANDARD_TEILWEISE_AB,
+            mitglieder_teilweise_ab: MITGLIEDER_TEILWEISE_AB,
+            mitglieder_vorschau: STANDARD_VORSCHAU,
+            pcm_fallback_erlaubt: false,
+            codec: CodecRichtlinie::default(),
+            anfrage_limits: AnfrageLimits::default(),
+            drosselung: DrosselLimits::default(),
+            replay: ReplayKonfig::default(),
+            soundboard: SoundboardLimits::default(),
+            einladungen: EinladungsLimits::default(),
+            mitschnitt: MitschnittKonfig::default(),
+            registrierung: RegistrierungsModus::default(),
+        }
+    }
+}
+
+impl SignalingConfig {
+    /// Start-Werte der zur Laufzeit aenderbaren Einstellungen
+    pub fn einstellungen(&self) -> ServerEinstellungen {
+        ServerEinstellungen {
+            name: self.server_name.clone(),
+            willkommensnachricht: self.welcome_message.clone(),
+            max_clients: self.max_clients,
+            host_nachricht: self.host_message.clone(),
+            chat_bearbeitungsfrist_sek: self.chat_bearbeitungsfrist_sek,
+        }
+    }
+}
+
+/// Zur Laufzeit aenderbare Server-Einstellungen (Clone teilt den Zustand)
+///
+/// Name, Willkommensnachricht, Client-Limit, Host-Nachricht und MOTD werden
+/// hier statt aus [`SignalingConfig`] gelesen, damit Aenderungen ueber den
+/// Commander sofort fuer neue Logins gelten.
+#[derive(Clone)]
+pub struct LaufzeitEinstellungen {
+    einstellungen: Arc<RwLock<ServerEinstellungen>>,
+    motd: Arc<RwLock<Motd>>,
+}
+
+impl LaufzeitEinstellungen {
+    pub fn neu(einstellungen: ServerEinstellungen, motd: Motd) -> Self {
+        Self {
+            einstellungen: Arc::new(RwLock::new(einstellungen)),
+            motd: Arc::new(RwLock::new(motd)),
+        }
+    }
+
+    /// Gibt die aktuellen Einstellungen zurueck
+    pub fn aktuell(&self) -> ServerEinstellungen {
+        self.einstellungen
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    /// Uebernimmt geaenderte Einstellungen
+    pub fn uebernehmen(&self, neu: ServerEinstellungen) {
+        tracing::info!(
+            name = %neu.name,
+            max_clients = neu.max_clients,
+            "Server-Einstellungen uebernommen"
+        );
+        *self
+            .einstellungen
+            .write()
+            .unwrap_or_else(|e| e.into_inner()) = neu;
+    }
+
+    /// Nachricht des Tages fuer Clients (`None` wenn keine gesetzt ist)
+    pub fn motd(&self) -> Option<speakeasy_protocol::control::Motd> {
+        let motd = self.motd.read().unwrap_or_else(|e| e.into_inner());
+        (!motd.ist_leer()).then(|| speakeasy_protocol::control::Motd {
+            markdown: motd.markdown.clone(),
+            version: motd.version,
+        })
+    }
+
+    /// Uebernimmt eine geaenderte Nachricht des Tages
+    ///
+    /// Aeltere Versionen werden ignoriert, falls Ereignisse ueberholt werden.
+    pub fn motd_uebernehmen(&self, neu: Motd) {
+        let mut motd = self.motd.write().unwrap_or_else(|e| e.into_inner());
+        if neu.version >= motd.version {
+            tracing::info!(version = neu.version, "MOTD uebernommen");
+            *motd = neu;
         }
     }
 }
@@ -83,14 +295,46 @@ where
     pub chat_service: Arc<ChatService<U>>,
     /// Voice-State (in-memory, UDP-Sessions)
     pub voice_state: VoiceState,
-    /// Channel-Router (Voice-Pakete weiterleiten)
+    /// Channel-Router (Voice-Pakete weiterleiten, Notfall-Stummschaltung)
     pub channel_router: ChannelRouter,
+    /// Soundboard (laufende Wiedergaben ueber `channel_router`, Drossel)
+    pub soundboard: SoundboardZustand,
     /// Presence-Manager (Wer ist online, in welchem Channel)
     pub presence: PresenceManager,
     /// Event-Broadcaster (Nachrichten an Clients senden)
     pub broadcaster: EventBroadcaster,
+    /// Langsam-Modus der Kanaele (Chat-Abstand je Benutzer)
+    pub langsammodus: Langsammodus,
+    /// Offene Kanal-Einladungen, Beitrittsanfragen und Zutritte
+    pub einladungen: Einladungen,
+    /// E2E-Schluessel der Clients und Epoch je Kanal
+    pub e2e: E2ESchluessel,
+    /// Letzte Benutzeraktivitaet (geteilt mit dem Voice-Server)
+    pub aktivitaet: AktivitaetsTracker,
+    /// AFK-Richtlinie (Laufzeit-Zustand)
+    pub afk: AfkWaechter,
+    /// Kicks und Server-Stopps mit Countdown
+    pub trennungen: AngekuendigteTrennungen,
+    /// Freigaben fuer Sitzungsmitschnitte
+    pub mitschnitte: Mitschnitte,
+    /// Server-Einstellungen (Laufzeit-Zustand)
+    pub einstellungen: LaufzeitEinstellungen,
+    /// Zaehler fuer Zeitueberschreitungen und abgebrochene Aufrufe
+    pub zeitlimit_metriken: ZeitlimitMetriken,
+    /// Laufende Anfragen pro Benutzer und serverweit
+    pub anfragen: AnfrageBegrenzer,
     /// Startzeitpunkt des Servers (fuer Uptime-Berechnung)
     pub start_time: Instant,
+    /// Gepuffertes Audit-Log (ohne: jedes Ereignis wird direkt geschrieben)
+    audit_sink: OnceLock<Arc<dyn AuditSink>>,
+    /// Datenexport und Kontoloeschung (ohne: beide werden abgelehnt)
+    konto_dienst: OnceLock<Arc<dyn KontoDienst>>,
+    /// Signierte Download-Links (ohne: Downloads werden abgelehnt)
+    datei_dienst: OnceLock<Arc<dyn DateiDienst>>,
+    /// Signierte Upload-Links (ohne: Uploads werden abgelehnt)
+    upload_dienst: OnceLock<Arc<dyn UploadDienst>>,
+    /// Soundboard-Clips (ohne: Abspielen wird abgelehnt)
+    sound_quelle: OnceLock<Arc<dyn SoundQuelle>>,
 }
 
 impl<U, P, B> SignalingState<U, P, B>
@@ -100,6 +344,9 @@ where
     B: BanRepository + 'static,
 {
     /// Erstellt einen neuen SignalingState
+    ///
+    /// `sprecher` und `notfall` werden mit dem Voice-Server geteilt.
+    #[allow(clippy::too_many_arguments)]
     pub fn neu(
         config: SignalingConfig,
         auth_service: Arc<AuthService<U>>,
@@ -107,7 +354,19 @@ where
         ban_service: Arc<BanService<B>>,
         db: Arc<U>,
         chat_service: Arc<ChatService<U>>,
+        aktivitaet: AktivitaetsTracker,
+        sprecher: SprecherTracker,
+        notfall: NotfallStumm,
     ) -> Arc<Self> {
+        let afk = AfkWaechter::neu(config.afk);
+        let einstellungen = LaufzeitEinstellungen::neu(config.einstellungen(), config.motd.clone());
+        let anfragen = AnfrageBegrenzer::neu(config.anfrage_limits);
+        let broadcaster = EventBroadcaster::mit_replay(config.replay);
+        let channel_router = ChannelRouter::mit_notfall(notfall);
+        let soundboard =
+            SoundboardZustand::neu(Soundboard::neu(channel_router.clone()), config.soundboard);
+        let einladungen = Einladungen::neu(config.einladungen);
+        let mitschnitte = Mitschnitte::neu(config.mitschnitt.clone());
         Arc::new(Self {
             config: Arc::new(config),
             auth_service,
@@ -115,16 +374,120 @@ where
             ban_service,
             db,
             chat_service,
-            voice_state: VoiceState::neu(),
-            channel_router: ChannelRouter::neu(),
-            presence: PresenceManager::neu(),
-            broadcaster: EventBroadcaster::neu(),
+            voice_state: VoiceState::mit_sprecher(sprecher),
+            channel_router,
+            soundboard,
+            presence: PresenceManager::mit_broadcaster(broadcaster.clone()),
+            broadcaster,
+            langsammodus: Langsammodus::neu(),
+            einladungen,
+            e2e: E2ESchluessel::neu(),
+            aktivitaet,
+            afk,
+            trennungen: AngekuendigteTrennungen::neu(),
+            mitschnitte,
+            einstellungen,
+            zeitlimit_metriken: ZeitlimitMetriken::neu(),
+            anfragen,
             start_time: Instant::now(),
+            audit_sink: OnceLock::new(),
+            konto_dienst: OnceLock::new(),
+            datei_dienst: OnceLock::new(),
+            upload_dienst: OnceLock::new(),
+            sound_quelle: OnceLock::new(),
         })
     }
 
+    /// Leitet Audit-Ereignisse ueber einen Sink (nur einmal moeglich)
+    pub fn audit_sink_setzen(&self, sink: Arc<dyn AuditSink>) {
+        if self.audit_sink.set(sink).is_err() {
+            tracing::warn!("Audit-Sink bereits gesetzt");
+        }
+    }
+
+    /// Setzt den Dienst fuer Datenexport und Kontoloeschung (nur einmal moeglich)
+    pub fn konto_dienst_setzen(&self, dienst: Arc<dyn KontoDienst>) {
+        if self.konto_dienst.set(dienst).is_err() {
+            tracing::warn!("Konto-Dienst bereits gesetzt");
+        }
+    }
+
+    /// Dienst fuer Datenexport und Kontoloeschung, falls gesetzt
+    pub fn konto_dienst(&self) -> Option<&Arc<dyn KontoDienst>> {
+        self.konto_dienst.get()
+    }
+
+    /// Setzt den Dienst fuer Download-Links (nur einmal moeglich)
+    pub fn datei_dienst_setzen(&self, dienst: Arc<dyn DateiDienst>) {
+        if self.datei_dienst.set(dienst).is_err() {
+            tracing::warn!("Datei-Dienst bereits gesetzt");
+        }
+    }
+
+    /// Dienst fuer Download-Links, falls gesetzt
+    pub fn datei_dienst(&self) -> Option<&Arc<dyn DateiDienst>> {
+        self.datei_dienst.get()
+    }
+
+    /// Setzt den Dienst fuer Upload-Links (nur einmal moeglich)
+    pub fn upload_dienst_setzen(&self, dienst: Arc<dyn UploadDienst>) {
+        if self.upload_dienst.set(dienst).is_err() {
+            tracing::warn!("Upload-Dienst bereits gesetzt");
+        }
+    }
+
+    /// Dienst fuer Upload-Links, falls gesetzt
+    pub fn upload_dienst(&self) -> Option<&Arc<dyn UploadDienst>> {
+        self.upload_dienst.get()
+    }
+
+    /// Setzt die Quelle der Soundboard-Clips (nur einmal moeglich)
+    pub fn sound_quelle_setzen(&self, quelle: Arc<dyn SoundQuelle>) {
+        if self.sound_quelle.set(quelle).is_err() {
+            tracing::warn!("Sound-Quelle bereits gesetzt");
+        }
+    }
+
+    /// Quelle der Soundboard-Clips, falls gesetzt
+    pub fn sound_quelle(&self) -> Option<&Arc<dyn SoundQuelle>> {
+        self.sound_quelle.get()
+    }
+
+    /// Uebernimmt geaenderte Server-Einstellungen (gilt ab dem naechsten Login)
+    pub fn einstellungen_uebernehmen(&self, neu: ServerEinstellungen) {
+        self.einstellungen.uebernehmen(neu);
+    }
+
     /// Gibt die Uptime in Sekunden zurueck
     pub fn uptime_sek(&self) -> u64 {
         self.start_time.elapsed().as_secs()
     }
 }
+
+impl<U, P, B> SignalingState<U, P, B>
+where
+    U: UserRepository
+        + ServerGroupRepository
+        + ChannelRepository
+        + ChatMessageRepository
+        + AuditLogRepository
+        + 'static,
+    P: PermissionRepository + 'static,
+    B: BanRepository + 'static,
+{
+    /// Protokolliert ein Ereignis im Audit-Log
+    ///
+    /// Ohne Sink wird direkt geschrieben. Fehler werden nur geloggt: ein
+    /// fehlender Audit-Eintrag macht die Aktion nicht rueckgaengig.
+    pub async fn audit_protokollieren(&self, eintrag: NeuerAuditEintrag) {
+        match self.audit_sink.get() {
+            Some(sink) => sink.protokollieren(eintrag),
+            None => {
+                let aktion = eintrag.action.clone();
+                if let Err(e) = self.db.log_events(&[eintrag]).await {
+                    tracing::warn!(aktion, fehler = %e, "Audit-Eintrag fehlgeschlagen");
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,93 @@
+//! Sitzungsablauf – Warnt verbundene Clients vor dem Ablauf ihrer Session
+//!
+//! Jede Verbindung prueft beim Keepalive-Ping die Restlaufzeit ihrer
+//! Session. Faellt sie unter `sitzungswarnung_sek`, geht einmal ein
+//! `SessionExpiryWarning` an den Client. Verlaengert er mit `SessionRenew`,
+//! steigt die Restlaufzeit wieder ueber die Schwelle und die naechste
+//! Warnung folgt erst kurz vor dem neuen Ablauf. Ohne Verlaengerung laeuft
+//! die Session ab und der Cleanup-Task des
+//! [`SessionStore`](speakeasy_auth::SessionStore) entfernt sie.
+
+use speakeasy_protocol::control::{ControlMessage, ControlPayload, SessionExpiryWarningEvent};
+
+/// Warnzustand einer Verbindung
+#[derive(Debug)]
+pub struct AblaufWarnung {
+    /// Restlaufzeit, ab der gewarnt wird (0 = nie)
+    schwelle: chrono::Duration,
+    /// Fuer den aktuellen Ablauf bereits gewarnt
+    gewarnt: bool,
+}
+
+impl AblaufWarnung {
+    pub fn neu(schwelle_sek: u64) -> Self {
+        Self {
+            schwelle: chrono::Duration::seconds(i64::try_from(schwelle_sek).unwrap_or(i64::MAX)),
+            gewarnt: false,
+        }
+    }
+
+    /// Gibt die Warnung zurueck, wenn sie fuer diese Restlaufzeit faellig ist
+    ///
+    /// Eine bereits abgelaufene Session erhaelt keine Warnung mehr.
+    pub fn pruefen(&mut self, restlaufzeit: chrono::Duration) -> Option<ControlMessage> {
+        if self.schwelle.is_zero() || restlaufzeit <= chrono::Duration::zero() {
+            return None;
+        }
+        if restlaufzeit > self.schwelle {
+            // Verlaengert (oder noch weit entfernt): naechsten Ablauf neu beobachten
+            self.gewarnt = false;
+            return None;
+        }
+        if self.gewarnt {
+            return None;
+        }
+        self.gewarnt = true;
+        Some(ControlMessage::new(
+            0,
+            ControlPayload::SessionExpiryWarning(SessionExpiryWarningEvent {
+                expires_in_secs: restlaufzeit.num_seconds().max(1) as u64,
+            }),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sekunden(s: i64) -> chrono::Duration {
+        chrono::Duration::seconds(s)
+    }
+
+    fn restsekunden(nachricht: ControlMessage) -> u64 {
+        match nachricht.payload {
+            ControlPayload::SessionExpiryWarning(w) => w.expires_in_secs,
+            andere => panic!("Erwartet SessionExpiryWarning, erhalten {andere:?}"),
+        }
+    }
+
+    #[test]
+    fn warnt_einmal_unter_der_schwelle() {
+        let mut warnung = AblaufWarnung::neu(600);
+        assert!(warnung.pruefen(sekunden(3600)).is_none());
+        assert_eq!(restsekunden(warnung.pruefen(sekunden(590)).unwrap()), 590);
+        assert!(warnung.pruefen(sekunden(560)).is_none());
+        // Abgelaufen: keine weitere Warnung
+        assert!(warnung.pruefen(sekunden(-5)).is_none());
+    }
+
+    #[test]
+    fn nach_verlaengerung_wird_erneut_gewarnt() {
+        let mut warnung = AblaufWarnung::neu(600);
+        assert!(warnung.pruefen(sekunden(300)).is_some());
+        assert!(warnung.pruefen(sekunden(86_400)).is_none());
+        assert!(warnung.pruefen(sekunden(120)).is_some());
+    }
+
+    #[test]
+    fn schwelle_null_warnt_nie() {
+        let mut warnung = AblaufWarnung::neu(0);
+        assert!(warnung.pruefen(sekunden(1)).is_none());
+    }
+}
@@ -0,0 +1,601 @@
+//! Soundboard – kurze Clips serverseitig in einen Kanal einspielen
+//!
+//! Clips werden beim Registrieren (Commander) einmalig zu Opus-Frames
+//! kodiert und in der Datenbank abgelegt. Beim Abspielen laedt dieses Modul
+//! die Frames ueber die [`SoundQuelle`], vergibt eine eigene SSRC und
+//! uebergibt sie dem [`Soundboard`] des Voice-Crates, das sie im Takt in den
+//! Kanal einspeist. Die Wiedergabe erscheint bei den Clients als
+//! Pseudo-Client mit `ClientInfo::soundboard`.
+//!
+//! Abspielen und Anhalten erfordern [`SOUNDBOARD_ABSPIELEN`] als
+//! ausdruecklichen Grant im Kanal (wie die Notfall-Stummschaltung: ohne
+//! Regel darf niemand). Zusaetzlich begrenzt die [`SoundboardDrossel`]
+//! gestartete Wiedergaben je Benutzer und je Kanal in einem gleitenden
+//! Zeitfenster.
+
+use dashmap::DashMap;
+use speakeasy_core::types::{ChannelId, UserId};
+use speakeasy_db::{
+    models::SoundRecord,
+    repository::{DbResult, UserRepository},
+    BanRepository, ChannelRepository, ChatMessageRepository, Datenbank, PermissionRepository,
+    ServerGroupRepository, SoundboardRepository, SqliteDb,
+};
+use speakeasy_protocol::control::{
+    ClientInfo, ControlMessage, ControlPayload, SoundboardPlaybackEvent,
+};
+use speakeasy_voice::Soundboard;
+use std::collections::{HashMap, VecDeque};
+use std::future::Future;
+use std::hash::Hash;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::time::Instant;
+use uuid::Uuid;
+
+use crate::error::{SignalingError, SignalingResult};
+use crate::handlers::voice_handler::freie_ssrc;
+use crate::notfall::ausdruecklich_gewaehrt;
+use crate::server_state::SignalingState;
+
+/// Berechtigung zum Abspielen und Anhalten von Soundboard-Clips
+pub const SOUNDBOARD_ABSPIELEN: &str = "b_soundboard_play";
+
+// ---------------------------------------------------------------------------
+// Drossel
+// ---------------------------------------------------------------------------
+
+/// Grenzen fuer gestartete Wiedergaben
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SoundboardLimits {
+    /// Wiedergaben je Benutzer im Zeitfenster (0 = unbegrenzt)
+    pub pro_benutzer: usize,
+    /// Wiedergaben je Kanal im Zeitfenster (0 = unbegrenzt)
+    pub pro_kanal: usize,
+    /// Laenge des gleitenden Zeitfensters
+    pub fenster: Duration,
+}
+
+impl Default for SoundboardLimits {
+    fn default() -> Self {
+        Self {
+            pro_benutzer: 3,
+            pro_kanal: 6,
+            fenster: Duration::from_secs(10),
+        }
+    }
+}
+
+/// Ab so vielen Eintraegen werden abgelaufene Zeitfenster aufgeraeumt
+const AUFRAEUMEN_AB: usize = 1024;
+
+/// Gleitendes Zeitfenster je Benutzer und je Kanal
+pub struct SoundboardDrossel {
+    limits: SoundboardLimits,
+    zustand: Mutex<DrosselZustand>,
+}
+
+#[derive(Default)]
+struct DrosselZustand {
+    benutzer: HashMap<UserId, VecDeque<Instant>>,
+    kanaele: HashMap<ChannelId, VecDeque<Instant>>,
+}
+
+impl SoundboardDrossel {
+    pub fn neu(limits: SoundboardLimits) -> Self {
+        Self {
+            limits,
+            zustand: Mutex::new(DrosselZustand::default()),
+        }
+    }
+
+    /// Verbucht eine Wiedergabe oder nennt die Wartezeit bis zur naechsten
+    ///
+    /// Eine abgelehnte Wiedergabe zaehlt bei keiner der beiden Grenzen.
+    pub fn pruefen(&self, user_id: UserId, channel_id: ChannelId) -> Result<(), Duration> {
+        let jetzt = Instant::now();
+        let fenster = self.limits.fenster;
+        let mut zustand = self.zustand.lock().unwrap_or_else(|e| e.into_inner());
+        let DrosselZustand { benutzer, kanaele } = &mut *zustand;
+
+        let warten = [
+            wartezeit(benutzer, user_id, self.limits.pro_benutzer, fenster, jetzt),
+            wartezeit(kanaele, channel_id, self.limits.pro_kanal, fenster, jetzt),
+        ]
+        .into_iter()
+        .flatten()
+        .max();
+        if let Some(warten) = warten {
+            return Err(warten);
+        }
+
+        benutzer.entry(user_id).or_default().push_back(jetzt);
+        kanaele.entry(channel_id).or_default().push_back(jetzt);
+        if benutzer.len() + kanaele.len() > AUFRAEUMEN_AB {
+            let aktuell = |z: &mut VecDeque<Instant>| {
+                z.back()
+                    .is_some_and(|letzter| jetzt.duration_since(*letzter) < fenster)
+            };
+            benutzer.retain(|_, z| aktuell(z));
+            kanaele.retain(|_, z| aktuell(z));
+        }
+        Ok(())
+    }
+}
+
+/// Entfernt abgelaufene Zeitpunkte und prueft die Grenze eines Schluessels
+pub(crate) fn wartezeit<K: Eq + Hash>(
+    zeitpunkte: &mut HashMap<K, VecDeque<Instant>>,
+    schluessel: K,
+    grenze: usize,
+    fenster: Duration,
+    jetzt: Instant,
+) -> Option<Duration> {
+    if grenze == 0 {
+        return None;
+    }
+    let liste = zeitpunkte.get_mut(&schluessel)?;
+    while liste
+        .front()
+        .is_some_and(|t| jetzt.duration_since(*t) >= fenster)
+    {
+        liste.pop_front();
+    }
+    let aeltester = liste.len().checked_sub(grenze).map(|i| liste[i])?;
+    Some(fenster - jetzt.duration_since(aeltester))
+}
+
+// ---------------------------------------------------------------------------
+// Clip-Quelle
+// ---------------------------------------------------------------------------
+
+/// Rueckgabe der [`SoundQuelle`]-Methoden
+pub type SoundFuture<'a, T> = Pin<Box<dyn Future<Output = DbResult<T>> + Send + 'a>>;
+
+/// Objektsichere Sicht auf das [`SoundboardRepository`]
+///
+/// Wie beim `DateiDienst` nur fuer konkrete Typen implementiert, damit der
+/// Signaling-State keinen weiteren Typ-Parameter braucht.
+pub trait SoundQuelle: Send + Sync {
+    /// Eintrag und Opus-Frames eines Sounds (`None` = unbekannt)
+    fn sound_laden(&self, id: Uuid) -> SoundFuture<'_, Option<(SoundRecord, Vec<Vec<u8>>)>>;
+}
+
+impl SoundQuelle for SqliteDb {
+    fn sound_laden(&self, id: Uuid) -> SoundFuture<'_, Option<(SoundRecord, Vec<Vec<u8>>)>> {
+        Box::pin(async move {
+            let Some(record) = SoundboardRepository::get_by_id(self, id).await? else {
+                return Ok(None);
+            };
+            let frames = SoundboardRepository::get_frames(self, id).await?;
+            Ok(frames.map(|frames| (record, frames)))
+        })
+    }
+}
+
+impl SoundQuelle for Datenbank {
+    fn sound_laden(&self, id: Uuid) -> SoundFuture<'_, Option<(SoundRecord, Vec<Vec<u8>>)>> {
+        Box::pin(async move {
+            let Some(record) = SoundboardRepository::get_by_id(self, id).await? else {
+                return Ok(None);
+            };
+            let frames = SoundboardRepository::get_frames(self, id).await?;
+            Ok(frames.map(|frames| (record, frames)))
+        })
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Laufzeit-Zustand
+// ---------------------------------------------------------------------------
+
+/// Soundboard-Zustand des Signaling-Service
+pub struct SoundboardZustand {
+    /// Wiedergabe im Voice-Router
+    pub wiedergabe: Soundboard,
+    /// Grenzen fuer gestartete Wiedergaben
+    pub drossel: SoundboardDrossel,
+    /// Laufende Wiedergaben (fuer spaetere Beitritte)
+    aktiv: DashMap<UserId, SoundboardPlaybackEvent>,
+}
+
+impl SoundboardZustand {
+    pub fn neu(wiedergabe: Soundboard, limits: SoundboardLimits) -> Self {
+        Self {
+            wiedergabe,
+            drossel: SoundboardDrossel::neu(limits),
+            aktiv: DashMap::new(),
+        }
+    }
+
+    /// Pseudo-Clients der laufenden Wiedergaben eines Kanals
+    pub fn clients_in(&self, channel_id: &ChannelId) -> Vec<ClientInfo> {
+        self.aktiv
+            .iter()
+            .filter(|e| &e.channel_id == channel_id)
+            .map(|e| e.client.clone())
+            .collect()
+    }
+}
+
+/// Startet einen Clip im Kanal
+///
+/// Prueft Berechtigung, Drossel und Geltungsbereich des Sounds. Das Event
+/// geht vor dem ersten Frame an den Kanal; nach dem Ende (regulaer oder
+/// angehalten) folgt es mit `active = false`.
+pub async fn abspielen<U, P, B>(
+    state: &Arc<SignalingState<U, P, B>>,
+    quelle: &dyn SoundQuelle,
+    actor_id: UserId,
+    channel_id: ChannelId,
+    sound_id: &str,
+) -> SignalingResult<SoundboardPlaybackEvent>
+where
+    U: UserRepository + ServerGroupRepository + ChannelRepository + ChatMessageRepository + 'static,
+    P: PermissionRepository + 'static,
+    B: BanRepository + 'static,
+{
+    let id = Uuid::parse_str(sound_id)
+        .map_err(|_| SignalingError::protokoll(format!("Ungueltige Sound-ID '{sound_id}'")))?;
+    berechtigung_pruefen(state, actor_id, channel_id).await?;
+    ChannelRepository::get_by_id(state.db.as_ref(), channel_id.inner())
+        .await
+        .map_err(|e| SignalingError::intern(e.to_string()))?
+        .ok_or_else(|| SignalingError::NichtGefunden(format!("Kanal {channel_id}")))?;
+
+    let (record, frames) = quelle
+        .sound_laden(id)
+        .await
+        .map_err(|e| SignalingError::intern(e.to_string()))?
+        .filter(|(record, _)| record.channel_id.is_none_or(|c| c == channel_id.inner()))
+        .ok_or_else(|| {
+            SignalingError::NichtGefunden(format!("Sound {sound_id} in diesem Kanal"))
+        })?;
+
+    state
+        .soundboard
+        .drossel
+        .pruefen(actor_id, channel_id)
+        .map_err(|warten| SignalingError::ZuHaeufig {
+            retry_after_secs: warten.as_secs_f64().ceil().max(1.0) as u64,
+        })?;
+
+    let wiedergabe_id = UserId::new();
+    let ssrc = freie_ssrc(&state.voice_state);
+    let event = SoundboardPlaybackEvent {
+        channel_id,
+        sound_id: record.id.to_string(),
+        started_by: actor_id,
+        client: ClientInfo {
+            user_id: wiedergabe_id,
+            username: "soundboard".into(),
+            display_name: format!("Soundboard: {}", record.name),
+            channel_id: Some(channel_id),
+            server_groups: Vec::new(),
+            is_muted: false,
+            is_deafened: false,
+            is_input_muted: false,
+            ssrc: Some(ssrc),
+            listen_only: false,
+            soundboard: true,
+        },
+        active: true,
+    };
+    state.soundboard.aktiv.insert(wiedergabe_id, event.clone());
+    state.broadcaster.an_channel_senden(
+        &channel_id,
+        ControlMessage::new(0, ControlPayload::SoundboardPlayback(event.clone())),
+    );
+
+    let fertig = state.soundboard.wiedergabe.abspielen(
+        wiedergabe_id,
+        channel_id,
+        ssrc,
+        Arc::new(frames),
+        Duration::from_millis(u64::from(record.frame_ms)),
+    );
+    let state_ende = Arc::clone(state);
+    tokio::spawn(async move {
+        let _ = fertig.await;
+        if let Some((_, mut ende)) = state_ende.soundboard.aktiv.remove(&wiedergabe_id) {
+            ende.active = false;
+            let channel_id = ende.channel_id;
+            state_ende.broadcaster.an_channel_senden(
+                &channel_id,
+                ControlMessage::new(0, ControlPayload::SoundboardPlayback(ende)),
+            );
+        }
+    });
+
+    tracing::info!(
+        actor = %actor_id,
+        channel_id = %channel_id,
+        sound = %record.name,
+        dauer_ms = record.duration_ms,
+        "Soundboard-Clip gestartet"
+    );
+    Ok(event)
+}
+
+/// Haelt eine Wiedergabe oder alle Wiedergaben eines Kanals an
+///
+/// Gibt die angehaltenen Wiedergaben zurueck; das Ende-Event verschickt der
+/// Task der jeweiligen Wiedergabe.
+pub async fn anhalten<U, P, B>(
+    state: &Arc<SignalingState<U, P, B>>,
+    actor_id: UserId,
+    channel_id: ChannelId,
+    wiedergabe_id: Option<UserId>,
+) -> SignalingResult<Vec<UserId>>
+where
+    U: UserRepository + ServerGroupRepository + ChannelRepository + ChatMessageRepository + 'static,
+    P: PermissionRepository + 'static,
+    B: BanRepository + 'static,
+{
+    berechtigung_pruefen(state, actor_id, channel_id).await?;
+    let wiedergabe = &state.soundboard.wiedergabe;
+    match wiedergabe_id {
+        Some(id) if wiedergabe.kanal_von(&id) == Some(channel_id) && wiedergabe.stoppen(&id) => {
+            Ok(vec![id])
+        }
+        Some(id) => Err(SignalingError::NichtGefunden(format!(
+            "Soundboard-Wiedergabe {id}"
+        ))),
+        None => Ok(wiedergabe.kanal_stoppen(&channel_id)),
+    }
+}
+
+async fn berechtigung_pruefen<U, P, B>(
+    state: &SignalingState<U, P, B>,
+    actor_id: UserId,
+    channel_id: ChannelId,
+) -> SignalingResult<()>
+where
+    U: UserRepository + ServerGroupRepository + ChannelRepository + ChatMessageRepository + 'static,
+    P: PermissionRepository + 'static,
+    B: BanRepository + 'static,
+{
+    if ausdruecklich_gewaehrt(state, actor_id, channel_id, SOUNDBOARD_ABSPIELEN).await {
+        Ok(())
+    } else {
+        Err(SignalingError::ZugriffVerweigert(
+            "Keine Berechtigung fuer das Soundboard".into(),
+        ))
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_hilfen::{client_anmelden, TestAufbau, TestState};
+    use speakeasy_db::models::{
+        BerechtigungsWert, BerechtigungsZiel, NeuerBenutzer, NeuerKanal, NeuerSound, TriState,
+    };
+    use speakeasy_protocol::voice::VoicePacket;
+    use tokio::sync::mpsc;
+
+    async fn state(limits: SoundboardLimits) -> TestState {
+        TestAufbau::default().soundboard(limits).bauen().await
+    }
+
+    /// Legt Kanal, Sound (3 Frames) und einen Benutzer im Kanal an
+    async fn szenario(
+        state: &TestState,
+    ) -> (ChannelId, String, UserId, mpsc::Receiver<ControlMessage>) {
+        let db = state.db.as_ref();
+        let kanal = ChannelRepository::create(
+            db,
+            NeuerKanal {
+                name: "Event",
+                ..Default::default()
+            },
+        )
+        .await
+        .map(|k| ChannelId(k.id))
+        .unwrap();
+        let frames = vec![vec![1u8; 30], vec![2u8; 30], vec![3u8; 30]];
+        let sound = SoundboardRepository::create(
+            db,
+            NeuerSound {
+                name: "Fanfare",
+                channel_id: None,
+                file_id: None,
+                frame_ms: 20,
+                frames: &frames,
+                created_by: None,
+            },
+        )
+        .await
+        .unwrap();
+        let user_id = UserId(
+            UserRepository::create(
+                db,
+                NeuerBenutzer {
+                    username: "moderator",
+                    password_hash: "hash",
+                },
+            )
+            .await
+            .unwrap()
+            .id,
+        );
+        client_anmelden(state, user_id, None);
+        let rx = state.broadcaster.client_registrieren(user_id);
+        state.presence.channel_beitreten(user_id, kanal);
+        state.broadcaster.channel_beitreten(user_id, kanal);
+        (kanal, sound.id.to_string(), user_id, rx)
+    }
+
+    async fn gewaehren(state: &TestState, user_id: UserId, kanal: ChannelId) {
+        state
+            .db
+            .set_permission(
+                &BerechtigungsZiel::Benutzer(user_id.inner()),
+                SOUNDBOARD_ABSPIELEN,
+                BerechtigungsWert::TriState(TriState::Grant),
+                Some(kanal.inner()),
+            )
+            .await
+            .unwrap();
+        state
+            .permission_service
+            .cache_invalidieren(user_id.inner(), kanal.inner())
+            .await;
+    }
+
+    fn wiedergabe_events(rx: &mut mpsc::Receiver<ControlMessage>) -> Vec<SoundboardPlaybackEvent> {
+        let mut events = Vec::new();
+        while let Ok(nachricht) = rx.try_recv() {
+            if let ControlPayload::SoundboardPlayback(event) = nachricht.payload {
+                events.push(event);
+            }
+        }
+        events
+    }
+
+    #[tokio::test]
+    async fn abspielen_speist_ein_und_meldet_start_und_ende() {
+        let state = state(SoundboardLimits::default()).await;
+        let (kanal, sound_id, moderator, mut rx) = szenario(&state).await;
+        let quelle = Arc::clone(state.sound_quelle().unwrap());
+
+        let err = abspielen(&state, quelle.as_ref(), moderator, kanal, &sound_id)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, SignalingError::ZugriffVerweigert(_)));
+
+        gewaehren(&state, moderator, kanal).await;
+        let mut voice_rx = state.channel_router.kanal_beitreten(
+            moderator,
+            kanal,
+            "127.0.0.1:40000".parse().unwrap(),
+        );
+        let event = abspielen(&state, quelle.as_ref(), moderator, kanal, &sound_id)
+            .await
+            .unwrap();
+        assert!(event.active && event.client.soundboard);
+        let ssrc = event.client.ssrc.unwrap();
+
+        // Spaet Beitretende sehen den Pseudo-Client samt SSRC
+        let antwort = crate::mitglieder::beitritts_antwort(&state, UserId::new(), kanal, false);
+        assert!(antwort
+            .clients
+            .iter()
+            .any(|c| c.soundboard && c.ssrc == Some(ssrc)));
+
+        for sequenz in 0..3u32 {
+            let bytes = voice_rx.recv().await.unwrap();
+            let paket = VoicePacket::decode(&bytes).unwrap();
+            assert_eq!((paket.header.ssrc, paket.header.sequence), (ssrc, sequenz));
+        }
+
+        // Nach dem letzten Frame folgt das Ende-Event
+        for _ in 0..50 {
+            if state.soundboard.clients_in(&kanal).is_empty() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        let events = wiedergabe_events(&mut rx);
+        assert_eq!(
+            events.iter().map(|e| e.active).collect::<Vec<_>>(),
+            [true, false]
+        );
+        assert_eq!(events[1].client.user_id, event.client.user_id);
+    }
+
+    #[tokio::test]
+    async fn spam_wird_abgelehnt_und_stop_haelt_an() {
+        let state = state(SoundboardLimits {
+            pro_benutzer: 2,
+            pro_kanal: 0,
+            fenster: Duration::from_secs(60),
+        })
+        .await;
+        let (kanal, sound_id, moderator, _rx) = szenario(&state).await;
+        gewaehren(&state, moderator, kanal).await;
+        let quelle = Arc::clone(state.sound_quelle().unwrap());
+
+        let erste = abspielen(&state, quelle.as_ref(), moderator, kanal, &sound_id)
+            .await
+            .unwrap();
+        abspielen(&state, quelle.as_ref(), moderator, kanal, &sound_id)
+            .await
+            .unwrap();
+        let err = abspielen(&state, quelle.as_ref(), moderator, kanal, &sound_id)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            SignalingError::ZuHaeufig {
+                retry_after_secs: 1..=60
+            }
+        ));
+
+        let angehalten = anhalten(&state, moderator, kanal, Some(erste.client.user_id))
+            .await
+            .unwrap();
+        assert_eq!(angehalten, [erste.client.user_id]);
+        assert!(matches!(
+            anhalten(&state, moderator, kanal, Some(erste.client.user_id)).await,
+            Err(SignalingError::NichtGefunden(_))
+        ));
+        assert_eq!(
+            anhalten(&state, moderator, kanal, None)
+                .await
+                .unwrap()
+                .len(),
+            1
+        );
+    }
+
+    fn drossel(pro_benutzer: usize, pro_kanal: usize) -> SoundboardDrossel {
+        SoundboardDrossel::neu(SoundboardLimits {
+            pro_benutzer,
+            pro_kanal,
+            fenster: Duration::from_secs(10),
+        })
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn spam_eines_benutzers_wird_gebremst() {
+        let drossel = drossel(3, 0);
+        let (a, kanal) = (UserId::new(), ChannelId::new());
+
+        for _ in 0..3 {
+            assert_eq!(drossel.pruefen(a, kanal), Ok(()));
+            tokio::time::advance(Duration::from_secs(1)).await;
+        }
+        // Der erste Clip ist 3 s her: noch 7 s bis zum naechsten
+        assert_eq!(drossel.pruefen(a, kanal), Err(Duration::from_secs(7)));
+        // Abgelehnte Versuche verlaengern die Sperre nicht
+        assert_eq!(drossel.pruefen(a, kanal), Err(Duration::from_secs(7)));
+        // Andere Benutzer sind nicht betroffen
+        assert_eq!(drossel.pruefen(UserId::new(), kanal), Ok(()));
+
+        tokio::time::advance(Duration::from_secs(7)).await;
+        assert_eq!(drossel.pruefen(a, kanal), Ok(()));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn kanalgrenze_gilt_ueber_alle_benutzer() {
+        let drossel = drossel(3, 4);
+        let kanal = ChannelId::new();
+
+        for _ in 0..4 {
+            assert_eq!(drossel.pruefen(UserId::new(), kanal), Ok(()));
+        }
+        let spaet = UserId::new();
+        assert_eq!(drossel.pruefen(spaet, kanal), Err(Duration::from_secs(10)));
+        // Die Ablehnung am Kanal zaehlt nicht gegen den Benutzer
+        assert_eq!(drossel.pruefen(spaet, ChannelId::new()), Ok(()));
+        assert_eq!(drossel.pruefen(spaet, ChannelId::new()), Ok(()));
+        assert_eq!(drossel.pruefen(spaet, ChannelId::new()), Ok(()));
+        assert!(drossel.pruefen(spaet, ChannelId::new()).is_err());
+    }
+}
@@ -0,0 +1,150 @@
+//! Sprecher-Meldungen – verteilt `ClientSpeaking` an die Kanalmitglieder
+//!
+//! Den Sprechzustand fuehrt der `SprecherTracker` aus dem Voice-Crate (gespeist
+//! vom UDP-Empfangspfad). Ein Hintergrund-Task leert dessen Warteschlange ueber
+//! eine `SprecherDrossel` und sendet jeden gemeldeten Wechsel an alle Clients
+//! im Kanal des Sprechers. Neue Mitglieder erhalten den aktuellen Stand mit
+//! der `ChannelJoinResponse`.
+
+use speakeasy_core::types::{ChannelId, UserId};
+use speakeasy_db::{
+    repository::UserRepository, BanRepository, ChannelRepository, ChatMessageRepository,
+    PermissionRepository, ServerGroupRepository,
+};
+use speakeasy_protocol::control::{ClientSpeakingEvent, ControlMessage, ControlPayload};
+use speakeasy_voice::sprecher::{SprecherAenderung, ABFRAGE_INTERVALL, MELDE_ABSTAND};
+use speakeasy_voice::SprecherDrossel;
+use std::sync::Arc;
+use tokio::time::Instant;
+
+use crate::server_state::SignalingState;
+
+/// Gibt alle Clients im Kanal zurueck, die gerade sprechen
+pub fn aktive_sprecher<U, P, B>(
+    state: &Arc<SignalingState<U, P, B>>,
+    channel_id: &ChannelId,
+) -> Vec<UserId>
+where
+    U: UserRepository + ServerGroupRepository + ChannelRepository + ChatMessageRepository + 'static,
+    P: PermissionRepository + 'static,
+    B: BanRepository + 'static,
+{
+    let mitglieder = state.presence.user_ids_in_channel(channel_id);
+    state
+        .voice_state
+        .sprecher()
+        .sprechende(mitglieder.iter(), Instant::now())
+}
+
+/// Sendet gemeldete Wechsel an den jeweiligen Kanal
+///
+/// Gibt die Anzahl der versendeten Events zurueck. Wechsel von Clients
+/// ausserhalb eines Kanals werden verworfen.
+pub fn aenderungen_senden<U, P, B>(
+    state: &Arc<SignalingState<U, P, B>>,
+    aenderungen: Vec<SprecherAenderung>,
+) -> usize
+where
+    U: UserRepository + ServerGroupRepository + ChannelRepository + ChatMessageRepository + 'static,
+    P: PermissionRepository + 'static,
+    B: BanRepository + 'static,
+{
+    let mut gesendet = 0;
+    for aenderung in aenderungen {
+        let Some(channel_id) = state.presence.channel_von_client(&aenderung.user_id) else {
+            continue;
+        };
+        state.broadcaster.an_channel_senden(
+            &channel_id,
+            ControlMessage::new(
+                0,
+                ControlPayload::ClientSpeaking(ClientSpeakingEvent {
+                    user_id: aenderung.user_id,
+                    channel_id,
+                    speaking: aenderung.spricht,
+                }),
+            ),
+        );
+        gesendet += 1;
+    }
+    gesendet
+}
+
+/// Hintergrund-Task: meldet Sprechwechsel bis zum Shutdown
+pub async fn sprecher_loop<U, P, B>(
+    state: Arc<SignalingState<U, P, B>>,
+    mut shutdown_rx: tokio::sync::watch::Receiver<bool>,
+) where
+    U: UserRepository + ServerGroupRepository + ChannelRepository + ChatMessageRepository + 'static,
+    P: PermissionRepository + 'static,
+    B: BanRepository + 'static,
+{
+    let mut drossel = SprecherDrossel::neu(state.voice_state.sprecher().clone(), MELDE_ABSTAND);
+    let mut intervall = tokio::time::interval(ABFRAGE_INTERVALL);
+    intervall.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+    loop {
+        tokio::select! {
+            _ = intervall.tick() => {
+                aenderungen_senden(&state, drossel.abfragen(Instant::now()));
+            }
+            Ok(()) = shutdown_rx.changed() => {
+                if *shutdown_rx.borrow() {
+                    break;
+                }
+            }
+        }
+    }
+    tracing::debug!("Sprecher-Meldungen beendet");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_hilfen::{neuer_client, test_state};
+
+    #[tokio::test]
+    async fn wechsel_gehen_nur_an_den_eigenen_kanal() {
+        let state = test_state().await;
+        let lobby = ChannelId::new();
+        let sprecher = neuer_client(&state, Some(lobby));
+        let zuhoerer = neuer_client(&state, Some(lobby));
+        let fremd = neuer_client(&state, Some(ChannelId::new()));
+        let mut rx_sprecher = state.broadcaster.client_registrieren(sprecher);
+        let mut rx_zuhoerer = state.broadcaster.client_registrieren(zuhoerer);
+        let mut rx_fremd = state.broadcaster.client_registrieren(fremd);
+        state.broadcaster.channel_beitreten(sprecher, lobby);
+        state.broadcaster.channel_beitreten(zuhoerer, lobby);
+
+        let mut drossel = SprecherDrossel::neu(state.voice_state.sprecher().clone(), MELDE_ABSTAND);
+        state.voice_state.sprecher().paket(sprecher, false);
+        assert_eq!(
+            aenderungen_senden(&state, drossel.abfragen(Instant::now())),
+            1
+        );
+        assert_eq!(aktive_sprecher(&state, &lobby), vec![sprecher]);
+
+        for rx in [&mut rx_sprecher, &mut rx_zuhoerer] {
+            match rx.try_recv().unwrap().payload {
+                ControlPayload::ClientSpeaking(ev) => {
+                    assert_eq!(ev.user_id, sprecher);
+                    assert_eq!(ev.channel_id, lobby);
+                    assert!(ev.speaking);
+                }
+                andere => panic!("unerwartet: {andere:?}"),
+            }
+        }
+        assert!(rx_fremd.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn clients_ohne_kanal_werden_nicht_gemeldet() {
+        let state = test_state().await;
+        let mut drossel = SprecherDrossel::neu(state.voice_state.sprecher().clone(), MELDE_ABSTAND);
+        state.voice_state.sprecher().paket(UserId::new(), false);
+        assert_eq!(
+            aenderungen_senden(&state, drossel.abfragen(Instant::now())),
+            0
+        );
+    }
+}
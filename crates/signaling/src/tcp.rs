@@ -10,17 +10,22 @@
 //! Dies ist korrekt fuer einen einzelnen Server-Prozess.
 
 use speakeasy_db::{
-    repository::UserRepository, BanRepository, ChannelRepository, ChatMessageRepository,
-    PermissionRepository, ServerGroupRepository,
+    repository::UserRepository, AuditLogRepository, BanRepository, ChannelRepository,
+    ChatMessageRepository, InviteRepository, PermissionRepository, ServerGroupRepository,
 };
 use std::net::SocketAddr;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::net::TcpListener;
 use tokio::task::LocalSet;
 
 use crate::connection::ClientConnection;
 use crate::server_state::SignalingState;
 
+/// Abstand der Herzschlaege der Accept-Loop, auch ohne neue Verbindungen
+pub const HERZSCHLAG_INTERVALL: Duration = Duration::from_secs(1);
+
 /// TCP-Signaling-Server
 ///
 /// Bindet einen TCP-Socket und akzeptiert Verbindungen in einer Loop.
@@ -33,17 +38,48 @@ where
 {
     state: Arc<SignalingState<U, P, B>>,
     bind_addr: SocketAddr,
+    bereit: Option<Bereitmeldung>,
+    herzschlag: Option<Arc<AtomicU64>>,
 }
 
+/// Rueckruf mit der gebundenen Adresse, sobald der Listener steht
+type Bereitmeldung = Box<dyn FnOnce(SocketAddr) + Send>;
+
 impl<U, P, B> SignalingServer<U, P, B>
 where
-    U: UserRepository + ServerGroupRepository + ChannelRepository + ChatMessageRepository + 'static,
+    U: UserRepository
+        + ServerGroupRepository
+        + ChannelRepository
+        + ChatMessageRepository
+        + InviteRepository
+        + AuditLogRepository
+        + 'static,
     P: PermissionRepository + 'static,
     B: BanRepository + 'static,
 {
     /// Erstellt einen neuen SignalingServer
     pub fn neu(state: Arc<SignalingState<U, P, B>>, bind_addr: SocketAddr) -> Self {
-        Self { state, bind_addr }
+        Self {
+            state,
+            bind_addr,
+            bereit: None,
+            herzschlag: None,
+        }
+    }
+
+    /// Meldet die gebundene Adresse, sobald der Listener Verbindungen annimmt
+    pub fn bei_bereitschaft(mut self, melden: impl FnOnce(SocketAddr) + Send + 'static) -> Self {
+        self.bereit = Some(Box::new(melden));
+        self
+    }
+
+    /// Schreibt bei jedem Durchlauf der Accept-Loop die Unix-Zeit in ms
+    ///
+    /// Ohne neue Verbindungen schlaegt die Loop alle [`HERZSCHLAG_INTERVALL`];
+    /// bleibt der Wert stehen, nimmt der Listener keine Verbindungen mehr an.
+    pub fn mit_herzschlag(mut self, herzschlag: Arc<AtomicU64>) -> Self {
+        self.herzschlag = Some(herzschlag);
+        self
     }
 
     /// Startet den TCP-Listener und akzeptiert Verbindungen
@@ -60,7 +96,7 @@ where
 
     /// Interne Accept-Loop (laeuft innerhalb der LocalSet)
     async fn accept_loop(
-        self,
+        mut self,
         mut shutdown_rx: tokio::sync::watch::Receiver<bool>,
     ) -> std::io::Result<()> {
         let listener = TcpListener::bind(self.bind_addr).await?;
@@ -70,8 +106,41 @@ where
             adresse = %lokale_addr,
             "TCP Signaling-Server gestartet"
         );
+        if let Some(melden) = self.bereit.take() {
+            melden(lokale_addr);
+        }
+
+        // AFK-Pruefung, Sprecher-Meldungen, E2E-Schluesselrotation und
+        // angekuendigte Trennungen laufen als lokale Tasks neben den Verbindungen
+        tokio::task::spawn_local(crate::afk::afk_loop(
+            Arc::clone(&self.state),
+            shutdown_rx.clone(),
+        ));
+        tokio::task::spawn_local(crate::sprecher::sprecher_loop(
+            Arc::clone(&self.state),
+            shutdown_rx.clone(),
+        ));
+        tokio::task::spawn_local(crate::schluesselrotation::schluesselrotation_loop(
+            Arc::clone(&self.state),
+            shutdown_rx.clone(),
+        ));
+        tokio::task::spawn_local(crate::trennung::trennungs_loop(
+            Arc::clone(&self.state),
+            shutdown_rx.clone(),
+        ));
+
+        let mut takt = tokio::time::interval(HERZSCHLAG_INTERVALL);
+        takt.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
 
         loop {
+            if let Some(herzschlag) = &self.herzschlag {
+                let jetzt = std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .map(|d| d.as_millis() as u64)
+                    .unwrap_or(0);
+                herzschlag.store(jetzt, Ordering::Relaxed);
+            }
+
             tokio::select! {
                 // Neue eingehende Verbindung
                 result = listener.accept() => {
@@ -79,10 +148,11 @@ where
                         Ok((stream, peer_addr)) => {
                             // Client-Limit pruefen
                             let online = self.state.presence.online_anzahl() as u32;
-                            if online >= self.state.config.max_clients {
+                            let max_clients = self.state.einstellungen.aktuell().max_clients;
+                            if online >= max_clients {
                                 tracing::warn!(
                                     peer = %peer_addr,
-                                    max = self.state.config.max_clients,
+                                    max = max_clients,
                                     "Server voll – Verbindung abgelehnt"
                                 );
                                 drop(stream);
@@ -109,6 +179,9 @@ where
                     }
                 }
 
+                // Herzschlag auch ohne neue Verbindungen
+                _ = takt.tick() => {}
+
                 // Shutdown-Signal
                 Ok(()) = shutdown_rx.changed() => {
                     if *shutdown_rx.borrow() {
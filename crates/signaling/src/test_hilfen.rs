@@ -0,0 +1,123 @@
+//! Gemeinsamer Test-Aufbau fuer den Signaling-Zustand
+//!
+//! Alle Modul-Tests bauen ihren [`TestState`] hierueber statt selbst
+//! [`SignalingState::neu`] aufzurufen; zusammengesetzt wird der Zustand
+//! wie beim Wiedergabe-Pruefstand ([`pruefstand_mit`]). Verbundene Clients
+//! legen sie mit [`client_anmelden`] bzw. [`neuer_client`] an.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use speakeasy_core::types::{ChannelId, UserId};
+use speakeasy_db::models::NeuerBenutzer;
+use speakeasy_db::{repository::UserRepository, zeitlimit::Zeitlimits, SqliteDb};
+
+use crate::afk::AfkRichtlinie;
+use crate::presence::ClientPresence;
+use crate::server_state::{SignalingConfig, SignalingState};
+use crate::soundboard::SoundboardLimits;
+use crate::wiedergabe::pruefstand_mit;
+
+/// Signaling-Zustand auf einer leeren In-Memory-Datenbank
+pub(crate) type TestState = Arc<SignalingState<SqliteDb, SqliteDb, SqliteDb>>;
+
+/// Erstellt einen [`TestState`] mit Standard-Konfiguration
+pub(crate) async fn test_state() -> TestState {
+    TestAufbau::default().bauen().await
+}
+
+/// Baut einen [`TestState`] mit den Einstellungen, die Tests variieren
+#[derive(Default)]
+pub(crate) struct TestAufbau {
+    config: SignalingConfig,
+    sound_quelle: bool,
+}
+
+impl TestAufbau {
+    /// Ersetzt die gesamte Konfiguration
+    pub(crate) fn config(mut self, config: SignalingConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// AFK-Kanal und Schwelle der AFK-Pruefung
+    pub(crate) fn afk_kanal(mut self, kanal: ChannelId, schwelle: Duration) -> Self {
+        self.config.afk = AfkRichtlinie {
+            schwelle,
+            afk_kanal: Some(kanal),
+        };
+        self
+    }
+
+    /// Krypto-Modus (`"none"`, `"dtls"`, `"e2e"`)
+    pub(crate) fn crypto_mode(mut self, modus: &str) -> Self {
+        self.config.crypto_mode = modus.into();
+        self
+    }
+
+    /// Zeitlimits der Datenbank-Zugriffe
+    pub(crate) fn zeitlimits(mut self, zeitlimits: Zeitlimits) -> Self {
+        self.config.zeitlimits = zeitlimits;
+        self
+    }
+
+    /// Grenzen des Soundboards; die Datenbank dient zugleich als Sound-Quelle
+    pub(crate) fn soundboard(mut self, limits: SoundboardLimits) -> Self {
+        self.config.soundboard = limits;
+        self.sound_quelle = true;
+        self
+    }
+
+    pub(crate) async fn bauen(self) -> TestState {
+        let state = pruefstand_mit(self.config)
+            .await
+            .expect("In-Memory-DB konnte nicht geoeffnet werden");
+        if self.sound_quelle {
+            state.sound_quelle_setzen(Arc::clone(&state.db) as _);
+        }
+        state
+    }
+}
+
+/// Meldet einen Client in der Presence an; mit `kanal` tritt er ihm bei
+pub(crate) fn client_anmelden(state: &TestState, user_id: UserId, kanal: Option<ChannelId>) {
+    state.presence.client_verbunden(ClientPresence {
+        user_id,
+        username: "test".into(),
+        display_name: "Test".into(),
+        channel_id: None,
+        is_input_muted: false,
+        is_output_muted: false,
+        is_away: false,
+        away_message: None,
+        nur_hoeren: false,
+        nur_hoeren_gewuenscht: false,
+        ip: None,
+    });
+    if let Some(kanal) = kanal {
+        state.presence.channel_beitreten(user_id, kanal);
+    }
+}
+
+/// Moderator mit Datenbank-Eintrag (Audit-Eintraege referenzieren den Akteur)
+pub(crate) async fn moderator(state: &TestState) -> UserId {
+    UserId(
+        UserRepository::create(
+            state.db.as_ref(),
+            NeuerBenutzer {
+                username: "moderator",
+                password_hash: "hash",
+            },
+        )
+        .await
+        .unwrap()
+        .id,
+    )
+}
+
+/// Wie [`client_anmelden`] fuer einen Client ohne Datenbank-Eintrag
+pub(crate) fn neuer_client(state: &TestState, kanal: Option<ChannelId>) -> UserId {
+    let user_id = UserId::new();
+    client_anmelden(state, user_id, kanal);
+    user_id
+}
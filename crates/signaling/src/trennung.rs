@@ -0,0 +1,663 @@
+//! Angekuendigte Trennungen – Kicks und Server-Stopps mit Countdown
+//!
+//! Mit `grace_secs` wird ein Kick oder Server-Stopp nicht sofort
+//! ausgefuehrt, sondern vorgemerkt. Die Betroffenen erhalten sofort eine
+//! `PendingDisconnectNotice` und erneut an den [`MELDEPUNKTE`]n; nach Ablauf
+//! fuehrt ein Hintergrund-Task die Aktion aus. Bis dahin kann sie mit
+//! `CancelPendingDisconnect` abgebrochen werden.
+//!
+//! Regeln:
+//! - Ein angekuendigter Kick verfaellt, wenn sich das Ziel vorher selbst
+//!   trennt (oder anderweitig getrennt wird)
+//! - Pro Ziel gibt es hoechstens eine Ankuendigung; eine neue ersetzt die
+//!   alte, ebenso beim Server-Stopp
+//! - Ankuendigen, Abbrechen und Ausfuehren landen im Audit-Log
+
+use speakeasy_core::types::UserId;
+use speakeasy_db::{
+    models::NeuerAuditEintrag, repository::UserRepository, AuditLogRepository, BanRepository,
+    ChannelRepository, ChatMessageRepository, PermissionRepository, ServerGroupRepository,
+};
+use speakeasy_protocol::control::{
+    ControlMessage, ControlPayload, PendingDisconnectCancelledEvent, PendingDisconnectKind,
+    PendingDisconnectNotice,
+};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::time::Instant;
+use uuid::Uuid;
+
+use crate::handlers::client_handler;
+use crate::server_state::SignalingState;
+
+/// Restsekunden, bei denen die Betroffenen erneut benachrichtigt werden
+pub const MELDEPUNKTE: [u32; 4] = [60, 30, 10, 5];
+/// Intervall der Hintergrund-Pruefung
+pub const PRUEF_INTERVALL: Duration = Duration::from_secs(1);
+
+/// Wen eine angekuendigte Trennung trifft
+#[derive(Debug, Clone)]
+pub enum TrennungsZiel {
+    /// Kick eines Benutzers (`nur_kanal` = nur aus dem aktuellen Kanal)
+    Benutzer { user_id: UserId, nur_kanal: bool },
+    /// Server-Stopp; nach Ablauf wird `shutdown_tx` ausgeloest
+    Server {
+        shutdown_tx: tokio::sync::watch::Sender<bool>,
+    },
+}
+
+/// Eine vorgemerkte Trennung
+#[derive(Debug, Clone)]
+pub struct Trennung {
+    pub action_id: String,
+    pub ziel: TrennungsZiel,
+    pub grund: Option<String>,
+    pub actor_id: UserId,
+    pub faellig: Instant,
+    /// Restsekunden der zuletzt verschickten Meldung
+    gemeldet: u32,
+}
+
+impl Trennung {
+    pub fn art(&self) -> PendingDisconnectKind {
+        match self.ziel {
+            TrennungsZiel::Benutzer {
+                nur_kanal: false, ..
+            } => PendingDisconnectKind::Kick,
+            TrennungsZiel::Benutzer {
+                nur_kanal: true, ..
+            } => PendingDisconnectKind::ChannelKick,
+            TrennungsZiel::Server { .. } => PendingDisconnectKind::ServerStop,
+        }
+    }
+
+    /// Berechtigung zum Ausloesen und Abbrechen
+    pub fn berechtigung(&self) -> &'static str {
+        match self.art() {
+            PendingDisconnectKind::Kick => "b_client_kick_server",
+            PendingDisconnectKind::ChannelKick => "b_client_kick_channel",
+            PendingDisconnectKind::ServerStop => "b_server_stop",
+        }
+    }
+
+    /// Verbleibende Sekunden bis zur Ausfuehrung (aufgerundet)
+    pub fn restsekunden(&self, jetzt: Instant) -> u32 {
+        let rest = self.faellig.saturating_duration_since(jetzt);
+        rest.as_millis().div_ceil(1000) as u32
+    }
+
+    pub fn meldung(&self, jetzt: Instant) -> PendingDisconnectNotice {
+        PendingDisconnectNotice {
+            action_id: self.action_id.clone(),
+            kind: self.art(),
+            reason: self.grund.clone(),
+            seconds_remaining: self.restsekunden(jetzt),
+        }
+    }
+
+    fn betrifft(&self, user_id: &UserId) -> bool {
+        matches!(&self.ziel, TrennungsZiel::Benutzer { user_id: ziel, .. } if ziel == user_id)
+    }
+
+    fn gleiches_ziel(&self, andere: &Trennung) -> bool {
+        match (&self.ziel, &andere.ziel) {
+            (
+                TrennungsZiel::Benutzer { user_id: a, .. },
+                TrennungsZiel::Benutzer { user_id: b, .. },
+            ) => a == b,
+            (TrennungsZiel::Server { .. }, TrennungsZiel::Server { .. }) => true,
+            _ => false,
+        }
+    }
+}
+
+/// Offene angekuendigte Trennungen (thread-safe)
+#[derive(Debug, Default)]
+pub struct AngekuendigteTrennungen {
+    offen: Mutex<HashMap<String, Trennung>>,
+}
+
+/// Ergebnis eines Pruefdurchlaufs
+#[derive(Debug, Default)]
+pub struct Faellig {
+    /// Erneut zu verschickende Meldungen
+    pub meldungen: Vec<(Trennung, PendingDisconnectNotice)>,
+    /// Abgelaufene, jetzt auszufuehrende Trennungen
+    pub ausfuehren: Vec<Trennung>,
+}
+
+impl AngekuendigteTrennungen {
+    pub fn neu() -> Self {
+        Self::default()
+    }
+
+    /// Merkt eine Trennung vor
+    ///
+    /// Gibt die neue Trennung und eine ersetzte Ankuendigung fuer dasselbe
+    /// Ziel zurueck.
+    pub fn vormerken(
+        &self,
+        ziel: TrennungsZiel,
+        grund: Option<String>,
+        actor_id: UserId,
+        frist: Duration,
+        jetzt: Instant,
+    ) -> (Trennung, Option<Trennung>) {
+        let trennung = Trennung {
+            action_id: Uuid::new_v4().to_string(),
+            ziel,
+            grund,
+            actor_id,
+            faellig: jetzt + frist,
+            gemeldet: frist.as_secs() as u32,
+        };
+        let mut offen = self.sperren();
+        let ersetzt_id = offen
+            .values()
+            .find(|t| t.gleiches_ziel(&trennung))
+            .map(|t| t.action_id.clone());
+        let ersetzt = ersetzt_id.and_then(|id| offen.remove(&id));
+        offen.insert(trennung.action_id.clone(), trennung.clone());
+        (trennung, ersetzt)
+    }
+
+    /// Gibt eine offene Trennung zurueck
+    pub fn offen(&self, action_id: &str) -> Option<Trennung> {
+        self.sperren().get(action_id).cloned()
+    }
+
+    /// Entfernt eine offene Trennung (Abbruch)
+    pub fn abbrechen(&self, action_id: &str) -> Option<Trennung> {
+        self.sperren().remove(action_id)
+    }
+
+    /// Verwirft angekuendigte Kicks eines getrennten Benutzers
+    ///
+    /// Gibt die Anzahl der verworfenen Ankuendigungen zurueck.
+    pub fn ziel_getrennt(&self, user_id: &UserId) -> usize {
+        let mut offen = self.sperren();
+        let vorher = offen.len();
+        offen.retain(|_, t| !t.betrifft(user_id));
+        let verworfen = vorher - offen.len();
+        if verworfen > 0 {
+            tracing::debug!(user_id = %user_id, "Angekuendigter Kick verfallen: Ziel getrennt");
+        }
+        verworfen
+    }
+
+    pub fn anzahl(&self) -> usize {
+        self.sperren().len()
+    }
+
+    /// Ermittelt faellige Meldungen und abgelaufene Trennungen
+    ///
+    /// Abgelaufene Trennungen werden dabei entfernt.
+    pub fn pruefen(&self, jetzt: Instant) -> Faellig {
+        let mut faellig = Faellig::default();
+        let mut offen = self.sperren();
+        let abgelaufen: Vec<String> = offen
+            .values()
+            .filter(|t| jetzt >= t.faellig)
+            .map(|t| t.action_id.clone())
+            .collect();
+        for id in abgelaufen {
+            if let Some(trennung) = offen.remove(&id) {
+                faellig.ausfuehren.push(trennung);
+            }
+        }
+        for trennung in offen.values_mut() {
+            let rest = trennung.restsekunden(jetzt);
+            if MELDEPUNKTE
+                .iter()
+                .any(|&punkt| rest <= punkt && punkt < trennung.gemeldet)
+            {
+                trennung.gemeldet = rest;
+                faellig
+                    .meldungen
+                    .push((trennung.clone(), trennung.meldung(jetzt)));
+            }
+        }
+        faellig
+    }
+
+    fn sperren(&self) -> std::sync::MutexGuard<'_, HashMap<String, Trennung>> {
+        self.offen.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Ablauf
+// ---------------------------------------------------------------------------
+
+/// Kuendigt eine Trennung an: vormerken, Betroffene benachrichtigen, protokollieren
+///
+/// Gibt die erste Meldung zurueck (Antwort an den Ausloeser).
+pub async fn ankuendigen<U, P, B>(
+    state: &Arc<SignalingState<U, P, B>>,
+    ziel: TrennungsZiel,
+    grund: Option<String>,
+    actor_id: UserId,
+    frist: Duration,
+) -> PendingDisconnectNotice
+where
+    U: UserRepository
+        + ServerGroupRepository
+        + ChannelRepository
+        + ChatMessageRepository
+        + AuditLogRepository
+        + 'static,
+    P: PermissionRepository + 'static,
+    B: BanRepository + 'static,
+{
+    let jetzt = Instant::now();
+    let (trennung, ersetzt) = state
+        .trennungen
+        .vormerken(ziel, grund, actor_id, frist, jetzt);
+    if let Some(ersetzt) = ersetzt {
+        tracing::debug!(action_id = %ersetzt.action_id, "Angekuendigte Trennung ersetzt");
+    }
+
+    let meldung = trennung.meldung(jetzt);
+    zustellen(
+        state,
+        &trennung,
+        ControlPayload::PendingDisconnectNotice(meldung.clone()),
+    );
+    tracing::info!(
+        actor = %actor_id,
+        action_id = %trennung.action_id,
+        art = ?trennung.art(),
+        frist_sek = frist.as_secs(),
+        "Trennung angekuendigt"
+    );
+    protokollieren(state, &trennung, actor_id, "trennung.angekuendigt").await;
+    meldung
+}
+
+/// Bricht eine angekuendigte Trennung ab und benachrichtigt die Betroffenen
+pub async fn abbrechen<U, P, B>(
+    state: &Arc<SignalingState<U, P, B>>,
+    action_id: &str,
+    actor_id: UserId,
+) -> Option<PendingDisconnectCancelledEvent>
+where
+    U: UserRepository
+        + ServerGroupRepository
+        + ChannelRepository
+        + ChatMessageRepository
+        + AuditLogRepository
+        + 'static,
+    P: PermissionRepository + 'static,
+    B: BanRepository + 'static,
+{
+    let trennung = state.trennungen.abbrechen(action_id)?;
+    let ereignis = PendingDisconnectCancelledEvent {
+        action_id: trennung.action_id.clone(),
+        kind: trennung.art(),
+    };
+    zustellen(
+        state,
+        &trennung,
+        ControlPayload::PendingDisconnectCancelled(ereignis.clone()),
+    );
+    tracing::info!(actor = %actor_id, action_id, "Angekuendigte Trennung abgebrochen");
+    protokollieren(state, &trennung, actor_id, "trennung.abgebrochen").await;
+    Some(ereignis)
+}
+
+/// Verschickt faellige Meldungen und fuehrt abgelaufene Trennungen aus
+///
+/// Gibt die IDs der ausgefuehrten Trennungen zurueck.
+pub async fn trennungen_bearbeiten<U, P, B>(
+    state: &Arc<SignalingState<U, P, B>>,
+    jetzt: Instant,
+) -> Vec<String>
+where
+    U: UserRepository
+        + ServerGroupRepository
+        + ChannelRepository
+        + ChatMessageRepository
+        + AuditLogRepository
+        + 'static,
+    P: PermissionRepository + 'static,
+    B: BanRepository + 'static,
+{
+    let faellig = state.trennungen.pruefen(jetzt);
+    for (trennung, meldung) in faellig.meldungen {
+        zustellen(
+            state,
+            &trennung,
+            ControlPayload::PendingDisconnectNotice(meldung),
+        );
+    }
+
+    let mut ausgefuehrt = Vec::new();
+    for trennung in faellig.ausfuehren {
+        let grund = trennung.grund.as_deref();
+        match &trennung.ziel {
+            TrennungsZiel::Benutzer { user_id, nur_kanal } => {
+                if !state.presence.ist_online(user_id) {
+                    tracing::debug!(user_id = %user_id, "Angekuendigter Kick: Ziel nicht mehr verbunden");
+                    continue;
+                }
+                client_handler::kick_ausfuehren(
+                    state,
+                    trennung.actor_id,
+                    *user_id,
+                    *nur_kanal,
+                    grund.unwrap_or("Gekickt"),
+                );
+            }
+            TrennungsZiel::Server { shutdown_tx } => {
+                tracing::warn!(
+                    actor = %trennung.actor_id,
+                    grund = grund.unwrap_or("Server wird gestoppt"),
+                    "Angekuendigter Server-Stopp wird ausgefuehrt"
+                );
+                let _ = shutdown_tx.send(true);
+            }
+        }
+        protokollieren(state, &trennung, trennung.actor_id, "trennung.ausgefuehrt").await;
+        ausgefuehrt.push(trennung.action_id);
+    }
+    ausgefuehrt
+}
+
+/// Hintergrund-Task: prueft periodisch bis zum Shutdown
+pub async fn trennungs_loop<U, P, B>(
+    state: Arc<SignalingState<U, P, B>>,
+    mut shutdown_rx: tokio::sync::watch::Receiver<bool>,
+) where
+    U: UserRepository
+        + ServerGroupRepository
+        + ChannelRepository
+        + ChatMessageRepository
+        + AuditLogRepository
+        + 'static,
+    P: PermissionRepository + 'static,
+    B: BanRepository + 'static,
+{
+    let mut intervall = tokio::time::interval(PRUEF_INTERVALL);
+    intervall.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+    loop {
+        tokio::select! {
+            _ = intervall.tick() => {
+                trennungen_bearbeiten(&state, Instant::now()).await;
+            }
+            Ok(()) = shutdown_rx.changed() => {
+                if *shutdown_rx.borrow() {
+                    break;
+                }
+            }
+        }
+    }
+    tracing::debug!("Pruefung angekuendigter Trennungen beendet");
+}
+
+/// Schickt eine Nachricht an alle Betroffenen einer Trennung
+fn zustellen<U, P, B>(state: &SignalingState<U, P, B>, trennung: &Trennung, payload: ControlPayload)
+where
+    U: UserRepository + ServerGroupRepository + ChannelRepository + ChatMessageRepository + 'static,
+    P: PermissionRepository + 'static,
+    B: BanRepository + 'static,
+{
+    let nachricht = ControlMessage::new(0, payload);
+    match &trennung.ziel {
+        TrennungsZiel::Benutzer { user_id, .. } => {
+            state.broadcaster.an_user_senden(user_id, nachricht);
+        }
+        TrennungsZiel::Server { .. } => {
+            state.broadcaster.an_alle_senden(nachricht);
+        }
+    }
+}
+
+async fn protokollieren<U, P, B>(
+    state: &SignalingState<U, P, B>,
+    trennung: &Trennung,
+    actor_id: UserId,
+    aktion: &str,
+) where
+    U: UserRepository
+        + ServerGroupRepository
+        + ChannelRepository
+        + ChatMessageRepository
+        + AuditLogRepository
+        + 'static,
+    P: PermissionRepository + 'static,
+    B: BanRepository + 'static,
+{
+    let (ziel_typ, ziel_id) = match &trennung.ziel {
+        TrennungsZiel::Benutzer { user_id, .. } => ("user", Some(user_id.to_string())),
+        TrennungsZiel::Server { .. } => ("server", None),
+    };
+    state
+        .audit_protokollieren(NeuerAuditEintrag::neu(
+            Some(actor_id.inner()),
+            aktion,
+            Some(ziel_typ),
+            ziel_id.as_deref(),
+            serde_json::json!({
+                "action_id": trennung.action_id,
+                "art": trennung.art(),
+                "grund": trennung.grund,
+            }),
+        ))
+        .await;
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_hilfen::{moderator, neuer_client, test_state, TestState};
+    use speakeasy_core::types::ChannelId;
+    use speakeasy_db::models::AuditLogFilter;
+
+    fn kick(user_id: UserId) -> TrennungsZiel {
+        TrennungsZiel::Benutzer {
+            user_id,
+            nur_kanal: false,
+        }
+    }
+
+    fn meldungen(rx: &mut tokio::sync::mpsc::Receiver<ControlMessage>) -> Vec<ControlPayload> {
+        let mut payloads = Vec::new();
+        while let Ok(nachricht) = rx.try_recv() {
+            payloads.push(nachricht.payload);
+        }
+        payloads
+    }
+
+    async fn audit_aktionen(state: &TestState) -> Vec<String> {
+        let mut aktionen: Vec<String> = state
+            .db
+            .list_events(AuditLogFilter::default())
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|e| e.action)
+            .collect();
+        aktionen.sort();
+        aktionen
+    }
+
+    #[test]
+    fn meldepunkte_einmal_je_punkt() {
+        let trennungen = AngekuendigteTrennungen::neu();
+        let start = Instant::now();
+        let (trennung, _) = trennungen.vormerken(
+            kick(UserId::new()),
+            Some("Spam".into()),
+            UserId::new(),
+            Duration::from_secs(30),
+            start,
+        );
+        assert_eq!(trennung.restsekunden(start), 30);
+
+        // 30 wurde schon beim Ankuendigen gemeldet
+        let mut gemeldet = Vec::new();
+        for sek in 0..30 {
+            let jetzt = start + Duration::from_millis(sek * 1000 + 300);
+            let faellig = trennungen.pruefen(jetzt);
+            assert!(faellig.ausfuehren.is_empty());
+            gemeldet.extend(faellig.meldungen.iter().map(|(_, m)| m.seconds_remaining));
+        }
+        assert_eq!(gemeldet, vec![10, 5]);
+
+        let faellig = trennungen.pruefen(start + Duration::from_secs(30));
+        assert_eq!(faellig.ausfuehren.len(), 1);
+        assert_eq!(trennungen.anzahl(), 0);
+    }
+
+    #[tokio::test]
+    async fn kick_wird_nach_ablauf_ausgefuehrt() {
+        let state = test_state().await;
+        let moderator = moderator(&state).await;
+        let ziel = neuer_client(&state, Some(ChannelId::new()));
+        let mut rx = state.broadcaster.client_registrieren(ziel);
+
+        let meldung = ankuendigen(
+            &state,
+            kick(ziel),
+            Some("Spam".into()),
+            moderator,
+            Duration::from_secs(60),
+        )
+        .await;
+        assert_eq!(meldung.seconds_remaining, 60);
+        assert_eq!(meldung.kind, PendingDisconnectKind::Kick);
+        let faellig = state.trennungen.offen(&meldung.action_id).unwrap().faellig;
+
+        // Kurz vor Ablauf: noch verbunden, Countdown bei 30, 10 und 5
+        for rest in [45, 30, 12, 10, 5, 1] {
+            let jetzt = faellig - Duration::from_secs(rest);
+            assert!(trennungen_bearbeiten(&state, jetzt).await.is_empty());
+        }
+        assert!(state.presence.ist_online(&ziel));
+        let countdown: Vec<u32> = meldungen(&mut rx)
+            .into_iter()
+            .filter_map(|p| match p {
+                ControlPayload::PendingDisconnectNotice(m) => {
+                    assert_eq!(m.action_id, meldung.action_id);
+                    assert_eq!(m.reason.as_deref(), Some("Spam"));
+                    Some(m.seconds_remaining)
+                }
+                _ => None,
+            })
+            .collect();
+        assert_eq!(countdown, vec![60, 30, 10, 5]);
+
+        assert_eq!(
+            trennungen_bearbeiten(&state, faellig).await,
+            vec![meldung.action_id.clone()]
+        );
+        assert!(!state.presence.ist_online(&ziel));
+        assert_eq!(state.trennungen.anzahl(), 0);
+        assert_eq!(
+            audit_aktionen(&state).await,
+            ["trennung.angekuendigt", "trennung.ausgefuehrt"]
+        );
+    }
+
+    #[tokio::test]
+    async fn abgebrochener_kick_findet_nicht_statt() {
+        let state = test_state().await;
+        let moderator = moderator(&state).await;
+        let ziel = neuer_client(&state, Some(ChannelId::new()));
+        let mut rx = state.broadcaster.client_registrieren(ziel);
+
+        let meldung =
+            ankuendigen(&state, kick(ziel), None, moderator, Duration::from_secs(30)).await;
+        let faellig = state.trennungen.offen(&meldung.action_id).unwrap().faellig;
+
+        let ereignis = abbrechen(&state, &meldung.action_id, moderator)
+            .await
+            .unwrap();
+        assert_eq!(ereignis.kind, PendingDisconnectKind::Kick);
+        assert!(abbrechen(&state, &meldung.action_id, moderator)
+            .await
+            .is_none());
+
+        assert!(trennungen_bearbeiten(&state, faellig).await.is_empty());
+        assert!(state.presence.ist_online(&ziel));
+        assert!(matches!(
+            meldungen(&mut rx).last(),
+            Some(ControlPayload::PendingDisconnectCancelled(e)) if e.action_id == meldung.action_id
+        ));
+        assert_eq!(
+            audit_aktionen(&state).await,
+            ["trennung.abgebrochen", "trennung.angekuendigt"]
+        );
+    }
+
+    #[tokio::test]
+    async fn kick_verfaellt_wenn_ziel_sich_trennt() {
+        let state = test_state().await;
+        let moderator = moderator(&state).await;
+        let ziel = neuer_client(&state, Some(ChannelId::new()));
+        let andere = neuer_client(&state, Some(ChannelId::new()));
+
+        ankuendigen(&state, kick(ziel), None, moderator, Duration::from_secs(30)).await;
+        let meldung = ankuendigen(
+            &state,
+            kick(andere),
+            None,
+            moderator,
+            Duration::from_secs(30),
+        )
+        .await;
+        let faellig = state.trennungen.offen(&meldung.action_id).unwrap().faellig;
+
+        // Selbst getrennt und sofort wieder verbunden: kein Kick der neuen Sitzung
+        assert_eq!(state.trennungen.ziel_getrennt(&ziel), 1);
+        assert_eq!(state.trennungen.anzahl(), 1);
+
+        assert_eq!(trennungen_bearbeiten(&state, faellig).await.len(), 1);
+        assert!(state.presence.ist_online(&ziel));
+        assert!(!state.presence.ist_online(&andere));
+    }
+
+    #[tokio::test]
+    async fn server_stopp_mit_countdown_an_alle() {
+        let state = test_state().await;
+        let moderator = moderator(&state).await;
+        let a = neuer_client(&state, Some(ChannelId::new()));
+        let b = neuer_client(&state, Some(ChannelId::new()));
+        let mut rx_a = state.broadcaster.client_registrieren(a);
+        let mut rx_b = state.broadcaster.client_registrieren(b);
+        let (shutdown_tx, shutdown_rx) = tokio::sync::watch::channel(false);
+
+        let meldung = ankuendigen(
+            &state,
+            TrennungsZiel::Server { shutdown_tx },
+            Some("Wartung".into()),
+            moderator,
+            Duration::from_secs(120),
+        )
+        .await;
+        assert_eq!(meldung.kind, PendingDisconnectKind::ServerStop);
+        let faellig = state.trennungen.offen(&meldung.action_id).unwrap().faellig;
+
+        trennungen_bearbeiten(&state, faellig - Duration::from_secs(60)).await;
+        assert!(!*shutdown_rx.borrow());
+        for rx in [&mut rx_a, &mut rx_b] {
+            let countdown: Vec<u32> = meldungen(rx)
+                .into_iter()
+                .filter_map(|p| match p {
+                    ControlPayload::PendingDisconnectNotice(m) => Some(m.seconds_remaining),
+                    _ => None,
+                })
+                .collect();
+            assert_eq!(countdown, vec![120, 60]);
+        }
+
+        trennungen_bearbeiten(&state, faellig).await;
+        assert!(*shutdown_rx.borrow());
+    }
+}
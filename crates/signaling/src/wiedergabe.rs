@@ -0,0 +1,569 @@
+//! Wiedergabe – Sitzungsmitschnitte gegen einen Server im Prozess abspielen
+//!
+//! Speist die Nachrichten eines [`Mitschnitt`]s in der aufgezeichneten
+//! Reihenfolge ueber den [`MessageDispatcher`] ein, je Verbindung mit
+//! eigenem Kontext und im Originaltempo, beschleunigt oder ohne Pausen.
+//! Ein Panic in einem Handler bricht die Wiedergabe mit dem betroffenen
+//! Eintrag ab. Am Ende steht ein [`Zustandsabbild`] ohne IDs, das sich mit
+//! einem erwarteten Abbild vergleichen laesst.
+//!
+//! ## IDs
+//! Der Server vergibt Benutzer-, Kanal- und Nachrichten-IDs bei jeder
+//! Wiedergabe neu. Die aufgezeichneten Antworten werden deshalb mit den
+//! tatsaechlichen verglichen: steht an gleicher Stelle eine andere UUID,
+//! wird die aufgezeichnete in allen folgenden Nachrichten ersetzt.
+//!
+//! ## Benutzer
+//! Fuer jeden Login im Mitschnitt wird vorab ein Konto mit dem Passwort
+//! [`GESCHWAERZT`] angelegt, passend zu den geschwaerzten Logins.
+
+use std::collections::{BTreeMap, HashMap};
+use std::panic::AssertUnwindSafe;
+use std::sync::Arc;
+use std::time::Duration;
+
+use futures_util::FutureExt;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use speakeasy_auth::{
+    ApiTokenStore, AuthError, AuthService, BanService, PermissionService, SessionStore,
+};
+use speakeasy_chat::ChatService;
+use speakeasy_db::{
+    repository::UserRepository, AuditLogRepository, BanRepository, ChannelRepository,
+    ChatMessageRepository, DbError, InviteRepository, PermissionRepository, ServerGroupRepository,
+    SqliteDb,
+};
+use speakeasy_protocol::control::{ControlMessage, ControlPayload, ErrorCode, ProtokollVersion};
+use speakeasy_voice::{AktivitaetsTracker, NotfallStumm, SprecherTracker};
+use tokio::sync::mpsc;
+use uuid::Uuid;
+
+use crate::anfragelimit::VerbindungsAnfragen;
+use crate::dispatcher::{DispatcherContext, MessageDispatcher};
+use crate::drosselung::VerbindungsDrossel;
+use crate::mitschnitt::{Mitschnitt, GESCHWAERZT};
+use crate::server_state::{SignalingConfig, SignalingState};
+
+/// Server im Prozess auf einer leeren In-Memory-Datenbank
+pub type Pruefstand = Arc<SignalingState<SqliteDb, SqliteDb, SqliteDb>>;
+
+/// Erstellt einen [`Pruefstand`] mit Standard-Konfiguration
+pub async fn pruefstand() -> Result<Pruefstand, DbError> {
+    pruefstand_mit(SignalingConfig::default()).await
+}
+
+/// Erstellt einen [`Pruefstand`] mit eigener Konfiguration
+///
+/// Auch die Modul-Tests bauen ihren Zustand hierueber, neue Abhaengigkeiten
+/// von [`SignalingState::neu`] werden also nur hier nachgezogen.
+pub async fn pruefstand_mit(config: SignalingConfig) -> Result<Pruefstand, DbError> {
+    let db = Arc::new(SqliteDb::in_memory().await?);
+    Ok(SignalingState::neu(
+        config,
+        Arc::new(AuthService::neu(
+            Arc::clone(&db),
+            SessionStore::neu(),
+            ApiTokenStore::neu(),
+        )),
+        PermissionService::neu(Arc::clone(&db)),
+        BanService::neu(Arc::clone(&db)),
+        Arc::clone(&db),
+        ChatService::neu(Arc::clone(&db)),
+        AktivitaetsTracker::neu(),
+        SprecherTracker::neu(),
+        NotfallStumm::neu(),
+    ))
+}
+
+/// Zeitverhalten der Wiedergabe
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Tempo {
+    /// Abstaende wie aufgezeichnet
+    Original,
+    /// Abstaende durch diesen Faktor geteilt
+    Faktor(f64),
+    /// Ohne Pausen
+    Sofort,
+}
+
+impl Tempo {
+    fn zeitpunkt(self, versatz_ms: u64) -> Option<Duration> {
+        let versatz = Duration::from_millis(versatz_ms);
+        match self {
+            Tempo::Original => Some(versatz),
+            Tempo::Faktor(faktor) if faktor > 0.0 => Some(versatz.div_f64(faktor)),
+            Tempo::Faktor(_) | Tempo::Sofort => None,
+        }
+    }
+}
+
+/// Fehler, der die Wiedergabe abbricht
+#[derive(Debug, thiserror::Error)]
+pub enum WiedergabeFehler {
+    #[error("Panic bei Eintrag {eintrag} (Verbindung {verbindung}, {nachricht}): {meldung}")]
+    Panik {
+        /// Nummer des Eintrags (1 = erste Nachricht nach dem Kopf)
+        eintrag: usize,
+        verbindung: u32,
+        /// Art der Nachricht
+        nachricht: String,
+        meldung: String,
+    },
+    #[error("Eintrag {eintrag}: {grund}")]
+    Nachricht { eintrag: usize, grund: String },
+    #[error("Datenbank: {0}")]
+    Datenbank(String),
+}
+
+/// Fehlerantwort des Servers waehrend der Wiedergabe
+///
+/// Bricht die Wiedergabe nicht ab; auch der Originalablauf kann Fehler
+/// enthalten.
+#[derive(Debug, Clone)]
+pub struct Fehlerantwort {
+    pub eintrag: usize,
+    pub verbindung: u32,
+    pub code: ErrorCode,
+    pub meldung: String,
+}
+
+/// Ergebnis einer vollstaendigen Wiedergabe
+#[derive(Debug, Clone)]
+pub struct WiedergabeBericht {
+    /// Eingespeiste Nachrichten
+    pub nachrichten: usize,
+    pub fehlerantworten: Vec<Fehlerantwort>,
+    /// Zustand nach der letzten Nachricht, vor dem Trennen
+    pub abbild: Zustandsabbild,
+}
+
+/// Angemeldeter Benutzer im [`Zustandsabbild`]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct ClientAbbild {
+    pub benutzername: String,
+    /// Kanalpfad (`None` = in keinem Kanal)
+    pub kanal: Option<String>,
+}
+
+/// Vergleichbarer Serverzustand ohne IDs
+///
+/// Kanaele erscheinen als Pfad (`Projekte/Alpha`). Verweist ein Kanal auf
+/// einen fehlenden Elternkanal, beginnt sein Pfad mit `?/`, bei einem
+/// Zyklus mit `.../`. Clients in einem geloeschten Kanal haben den Kanal `?`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Zustandsabbild {
+    pub kanaele: Vec<String>,
+    pub clients: Vec<ClientAbbild>,
+}
+
+impl Zustandsabbild {
+    /// Erfasst Kanalbaum (Datenbank) und Presence
+    pub async fn erfassen<U, P, B>(
+        state: &Arc<SignalingState<U, P, B>>,
+    ) -> Result<Self, WiedergabeFehler>
+    where
+        U: UserRepository
+            + ServerGroupRepository
+            + ChannelRepository
+            + ChatMessageRepository
+            + 'static,
+        P: PermissionRepository + 'static,
+        B: BanRepository + 'static,
+    {
+        let kanaele = ChannelRepository::list(state.db.as_ref())
+            .await
+            .map_err(|e| WiedergabeFehler::Datenbank(e.to_string()))?;
+        let eltern: HashMap<Uuid, (String, Option<Uuid>)> = kanaele
+            .iter()
+            .map(|k| (k.id, (k.name.clone(), k.parent_id)))
+            .collect();
+
+        let mut abbild = Self {
+            kanaele: kanaele.iter().map(|k| kanalpfad(&eltern, k.id)).collect(),
+            clients: state
+                .presence
+                .alle_clients()
+                .into_iter()
+                .map(|c| ClientAbbild {
+                    benutzername: c.username,
+                    kanal: c.channel_id.map(|k| kanalpfad(&eltern, k.inner())),
+                })
+                .collect(),
+        };
+        abbild.kanaele.sort();
+        abbild.clients.sort();
+        Ok(abbild)
+    }
+
+    /// Beschreibt die Unterschiede zu einem anderen Abbild (leer = gleich)
+    pub fn abweichungen(&self, tatsaechlich: &Self) -> Vec<String> {
+        let mut abweichungen = Vec::new();
+        for kanal in &self.kanaele {
+            if !tatsaechlich.kanaele.contains(kanal) {
+                abweichungen.push(format!("Kanal fehlt: {kanal}"));
+            }
+        }
+        for kanal in &tatsaechlich.kanaele {
+            if !self.kanaele.contains(kanal) {
+                abweichungen.push(format!("Unerwarteter Kanal: {kanal}"));
+            }
+        }
+        for client in &self.clients {
+            if !tatsaechlich.clients.contains(client) {
+                abweichungen.push(format!("Client fehlt: {client:?}"));
+            }
+        }
+        for client in &tatsaechlich.clients {
+            if !self.clients.contains(client) {
+                abweichungen.push(format!("Unerwarteter Client: {client:?}"));
+            }
+        }
+        abweichungen
+    }
+}
+
+fn kanalpfad(eltern: &HashMap<Uuid, (String, Option<Uuid>)>, id: Uuid) -> String {
+    let mut teile = Vec::new();
+    let mut aktuell = Some(id);
+    while let Some(kanal) = aktuell {
+        if teile.len() > eltern.len() {
+            teile.push("...".to_string());
+            break;
+        }
+        match eltern.get(&kanal) {
+            Some((name, parent)) => {
+                teile.push(name.clone());
+                aktuell = *parent;
+            }
+            None => {
+                teile.push("?".to_string());
+                break;
+            }
+        }
+    }
+    teile.reverse();
+    teile.join("/")
+}
+
+/// Zuordnung aufgezeichneter zu neu vergebenen UUIDs
+#[derive(Debug, Default)]
+struct IdZuordnung(HashMap<Uuid, Uuid>);
+
+impl IdZuordnung {
+    /// Vergleicht aufgezeichnete mit tatsaechlichen Antworten
+    fn abgleichen(&mut self, aufgezeichnet: &Value, tatsaechlich: &Value) {
+        match (aufgezeichnet, tatsaechlich) {
+            (Value::String(alt), Value::String(neu)) if alt != neu => {
+                if let (Ok(alt), Ok(neu)) = (alt.parse::<Uuid>(), neu.parse::<Uuid>()) {
+                    self.0.entry(alt).or_insert(neu);
+                }
+            }
+            (Value::Array(alt), Value::Array(neu)) => {
+                for (a, n) in alt.iter().zip(neu) {
+                    self.abgleichen(a, n);
+                }
+            }
+            (Value::Object(alt), Value::Object(neu)) => {
+                for (schluessel, a) in alt {
+                    if let Some(n) = neu.get(schluessel) {
+                        self.abgleichen(a, n);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn einsetzen(&self, wert: &mut Value) {
+        match wert {
+            Value::String(text) => {
+                if let Some(neu) = text.parse::<Uuid>().ok().and_then(|id| self.0.get(&id)) {
+                    *text = neu.to_string();
+                }
+            }
+            Value::Array(werte) => werte.iter_mut().for_each(|w| self.einsetzen(w)),
+            Value::Object(felder) => felder.values_mut().for_each(|w| self.einsetzen(w)),
+            _ => {}
+        }
+    }
+}
+
+/// Eine wiedergegebene Verbindung
+struct Verbindung {
+    ctx: DispatcherContext,
+    /// Broadcast-Queue nach dem Login; Ereignisse werden verworfen
+    ereignisse: Option<mpsc::Receiver<ControlMessage>>,
+}
+
+impl Verbindung {
+    fn neu(nummer: u32) -> Self {
+        Self {
+            ctx: DispatcherContext {
+                peer_addr: ([127, 0, 0, 1], 40_000 + (nummer % 20_000) as u16).into(),
+                session_token: None,
+                user_id: None,
+                // Mitschnitte beginnen nicht zwingend mit dem Hello
+                protokoll: Some(ProtokollVersion::AKTUELL),
+                shutdown_tx: tokio::sync::watch::channel(false).0,
+                zwischenmeldungen: Vec::new(),
+                anfragen: VerbindungsAnfragen::neu(),
+                drossel: VerbindungsDrossel::neu(Default::default()),
+            },
+            ereignisse: None,
+        }
+    }
+}
+
+/// Spielt einen Mitschnitt ab
+///
+/// Laeuft in einer eigenen `LocalSet` (schreibende Handler). Am Ende werden
+/// alle Verbindungen wie beim Trennen bereinigt.
+pub async fn wiedergeben<U, P, B>(
+    state: &Arc<SignalingState<U, P, B>>,
+    mitschnitt: &Mitschnitt,
+    tempo: Tempo,
+) -> Result<WiedergabeBericht, WiedergabeFehler>
+where
+    U: UserRepository
+        + ServerGroupRepository
+        + ChannelRepository
+        + ChatMessageRepository
+        + InviteRepository
+        + AuditLogRepository
+        + 'static,
+    P: PermissionRepository + 'static,
+    B: BanRepository + 'static,
+{
+    tokio::task::LocalSet::new()
+        .run_until(abspielen(state, mitschnitt, tempo))
+        .await
+}
+
+async fn abspielen<U, P, B>(
+    state: &Arc<SignalingState<U, P, B>>,
+    mitschnitt: &Mitschnitt,
+    tempo: Tempo,
+) -> Result<WiedergabeBericht, WiedergabeFehler>
+where
+    U: UserRepository
+        + ServerGroupRepository
+        + ChannelRepository
+        + ChatMessageRepository
+        + InviteRepository
+        + AuditLogRepository
+        + 'static,
+    P: PermissionRepository + 'static,
+    B: BanRepository + 'static,
+{
+    benutzer_anlegen(state, mitschnitt).await?;
+
+    let dispatcher = MessageDispatcher::neu(Arc::clone(state));
+    let mut verbindungen: BTreeMap<u32, Verbindung> = BTreeMap::new();
+    let mut ids = IdZuordnung::default();
+    let mut fehlerantworten = Vec::new();
+    let start = tokio::time::Instant::now();
+
+    for (index, eintrag) in mitschnitt.eintraege.iter().enumerate() {
+        let nummer = index + 1;
+        if let Some(zeitpunkt) = tempo.zeitpunkt(eintrag.versatz_ms) {
+            tokio::time::sleep_until(start + zeitpunkt).await;
+        }
+
+        let nachricht = nachricht_einsetzen(&ids, &eintrag.nachricht).map_err(|grund| {
+            WiedergabeFehler::Nachricht {
+                eintrag: nummer,
+                grund,
+            }
+        })?;
+        let art = variante(&nachricht.payload);
+        let verbindung = verbindungen
+            .entry(eintrag.verbindung)
+            .or_insert_with(|| Verbindung::neu(eintrag.verbindung));
+
+        let antwort = AssertUnwindSafe(dispatcher.dispatch(nachricht, &mut verbindung.ctx))
+            .catch_unwind()
+            .await
+            .map_err(|panik| WiedergabeFehler::Panik {
+                eintrag: nummer,
+                verbindung: eintrag.verbindung,
+                nachricht: art,
+                meldung: panik_meldung(panik.as_ref()),
+            })?;
+        let mut antworten = std::mem::take(&mut verbindung.ctx.zwischenmeldungen);
+        antworten.extend(antwort);
+
+        for (aufgezeichnet, tatsaechlich) in eintrag.antworten.iter().zip(&antworten) {
+            if let (Ok(alt), Ok(neu)) = (
+                serde_json::to_value(aufgezeichnet),
+                serde_json::to_value(tatsaechlich),
+            ) {
+                ids.abgleichen(&alt, &neu);
+            }
+        }
+        for antwort in &antworten {
+            if let ControlPayload::Error(fehler) = &antwort.payload {
+                tracing::debug!(eintrag = nummer, code = ?fehler.code, "Fehlerantwort");
+                fehlerantworten.push(Fehlerantwort {
+                    eintrag: nummer,
+                    verbindung: eintrag.verbindung,
+                    code: fehler.code,
+                    meldung: fehler.message.clone(),
+                });
+            }
+        }
+
+        // Wie die echte Verbindung: nach dem Login die Broadcast-Queue abonnieren
+        if let Some(uid) = verbindung.ctx.user_id {
+            if !state.broadcaster.ist_registriert(&uid) {
+                verbindung.ereignisse = Some(state.broadcaster.client_registrieren(uid));
+            }
+        }
+        for verbindung in verbindungen.values_mut() {
+            if let Some(ereignisse) = verbindung.ereignisse.as_mut() {
+                while ereignisse.try_recv().is_ok() {}
+            }
+        }
+    }
+
+    let abbild = Zustandsabbild::erfassen(state).await?;
+    for verbindung in verbindungen.values() {
+        if let Some(uid) = verbindung.ctx.user_id {
+            dispatcher.client_cleanup(&uid).await;
+        }
+    }
+    Ok(WiedergabeBericht {
+        nachrichten: mitschnitt.eintraege.len(),
+        fehlerantworten,
+        abbild,
+    })
+}
+
+/// Legt fuer jeden Login im Mitschnitt ein Konto an
+async fn benutzer_anlegen<U, P, B>(
+    state: &Arc<SignalingState<U, P, B>>,
+    mitschnitt: &Mitschnitt,
+) -> Result<(), WiedergabeFehler>
+where
+    U: UserRepository + ServerGroupRepository + ChannelRepository + ChatMessageRepository + 'static,
+    P: PermissionRepository + 'static,
+    B: BanRepository + 'static,
+{
+    for eintrag in &mitschnitt.eintraege {
+        let ControlPayload::Login(anfrage) = &eintrag.nachricht.payload else {
+            continue;
+        };
+        match state
+            .auth_service
+            .registrieren(&anfrage.username, GESCHWAERZT)
+            .await
+        {
+            Ok(_) | Err(AuthError::BenutzernameVergeben(_)) => {}
+            Err(e) => return Err(WiedergabeFehler::Datenbank(e.to_string())),
+        }
+    }
+    Ok(())
+}
+
+fn nachricht_einsetzen(
+    ids: &IdZuordnung,
+    nachricht: &ControlMessage,
+) -> Result<ControlMessage, String> {
+    let mut wert = serde_json::to_value(nachricht).map_err(|e| e.to_string())?;
+    ids.einsetzen(&mut wert);
+    serde_json::from_value(wert).map_err(|e| e.to_string())
+}
+
+/// Name der Nachrichtenart (`type`-Tag der Serialisierung)
+fn variante(payload: &ControlPayload) -> String {
+    serde_json::to_value(payload)
+        .ok()
+        .and_then(|wert| wert.get("type")?.as_str().map(str::to_string))
+        .unwrap_or_else(|| "unbekannt".into())
+}
+
+fn panik_meldung(panik: &(dyn std::any::Any + Send)) -> String {
+    panik
+        .downcast_ref::<&str>()
+        .map(|s| s.to_string())
+        .or_else(|| panik.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "unbekannter Panic".into())
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Zwei Benutzer bauen einen Kanalbaum um; der geloeschte Kanal war besetzt
+    const KANALBAUM_UMBAU: &str = include_str!("../mitschnitte/kanalbaum_umbau.ndjson");
+    const KANALBAUM_UMBAU_ABBILD: &str = include_str!("../mitschnitte/kanalbaum_umbau.abbild.json");
+
+    #[tokio::test]
+    async fn kanalbaum_umbau_ergibt_erwartetes_abbild() {
+        let mitschnitt = Mitschnitt::lesen(KANALBAUM_UMBAU).unwrap();
+        let erwartet: Zustandsabbild = serde_json::from_str(KANALBAUM_UMBAU_ABBILD).unwrap();
+
+        let state = pruefstand().await.unwrap();
+        let bericht = wiedergeben(&state, &mitschnitt, Tempo::Sofort)
+            .await
+            .unwrap();
+
+        assert_eq!(bericht.nachrichten, mitschnitt.eintraege.len());
+        assert!(
+            bericht.fehlerantworten.is_empty(),
+            "{:?}",
+            bericht.fehlerantworten
+        );
+        assert_eq!(erwartet.abweichungen(&bericht.abbild), Vec::<String>::new());
+        // Nach der Wiedergabe sind alle Verbindungen getrennt
+        assert_eq!(state.presence.online_anzahl(), 0);
+    }
+
+    #[test]
+    fn tempo_teilt_den_versatz() {
+        assert_eq!(
+            Tempo::Original.zeitpunkt(1200),
+            Some(Duration::from_millis(1200))
+        );
+        assert_eq!(
+            Tempo::Faktor(4.0).zeitpunkt(1200),
+            Some(Duration::from_millis(300))
+        );
+        assert_eq!(Tempo::Faktor(0.0).zeitpunkt(1200), None);
+        assert_eq!(Tempo::Sofort.zeitpunkt(1200), None);
+    }
+
+    #[test]
+    fn ids_werden_an_gleicher_stelle_zugeordnet() {
+        let alt = Uuid::new_v4();
+        let neu = Uuid::new_v4();
+        let mut ids = IdZuordnung::default();
+        ids.abgleichen(
+            &serde_json::json!({"channel_id": alt.to_string(), "name": "Alpha"}),
+            &serde_json::json!({"channel_id": neu.to_string(), "name": "Alpha"}),
+        );
+
+        let mut folgende = serde_json::json!({"ziele": [alt.to_string(), "kein-uuid"]});
+        ids.einsetzen(&mut folgende);
+        assert_eq!(
+            folgende,
+            serde_json::json!({"ziele": [neu.to_string(), "kein-uuid"]})
+        );
+    }
+
+    #[test]
+    fn kanalpfad_zeigt_fehlende_eltern_und_zyklen() {
+        let (a, b, c) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        let eltern = HashMap::from([
+            (a, ("A".to_string(), Some(b))),
+            (b, ("B".to_string(), Some(a))),
+            (c, ("C".to_string(), Some(Uuid::new_v4()))),
+        ]);
+        assert!(kanalpfad(&eltern, a).starts_with(".../"));
+        assert_eq!(kanalpfad(&eltern, c), "?/C");
+    }
+}
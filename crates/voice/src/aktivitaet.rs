@@ -0,0 +1,98 @@
+//! Aktivitaets-Tracker – Zeitpunkt der letzten Benutzeraktivitaet
+//!
+//! Wird von mehreren Quellen gespeist (Sprachpakete mit Speaking-Status,
+//! Chat, explizite Aktivitaets-Pings des Clients) und dient der
+//! automatischen AFK-Erkennung im Signaling-Service.
+//!
+//! Verwendet `tokio::time::Instant`, damit Tests die Uhr per
+//! `tokio::time::pause()`/`advance()` steuern koennen.
+
+use dashmap::DashMap;
+use speakeasy_core::types::UserId;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// Letzte Aktivitaet pro Benutzer (thread-safe, Clone teilt den Zustand)
+#[derive(Clone, Default)]
+pub struct AktivitaetsTracker {
+    inner: Arc<DashMap<UserId, Instant>>,
+}
+
+impl AktivitaetsTracker {
+    /// Erstellt einen leeren Tracker
+    pub fn neu() -> Self {
+        Self::default()
+    }
+
+    /// Vermerkt Aktivitaet zum aktuellen Zeitpunkt
+    pub fn melden(&self, user_id: UserId) {
+        self.melden_um(user_id, Instant::now());
+    }
+
+    /// Vermerkt Aktivitaet zu einem bestimmten Zeitpunkt
+    pub fn melden_um(&self, user_id: UserId, zeitpunkt: Instant) {
+        self.inner.insert(user_id, zeitpunkt);
+    }
+
+    /// Erfasst einen Benutzer nur, falls noch kein Eintrag existiert
+    pub fn erfassen_falls_neu(&self, user_id: UserId, zeitpunkt: Instant) {
+        self.inner.entry(user_id).or_insert(zeitpunkt);
+    }
+
+    /// Gibt den Zeitpunkt der letzten Aktivitaet zurueck
+    pub fn letzte_aktivitaet(&self, user_id: &UserId) -> Option<Instant> {
+        self.inner.get(user_id).map(|r| *r)
+    }
+
+    /// Gibt zurueck wie lange ein Benutzer bis `jetzt` inaktiv ist
+    pub fn inaktiv_seit(&self, user_id: &UserId, jetzt: Instant) -> Option<Duration> {
+        self.letzte_aktivitaet(user_id)
+            .map(|t| jetzt.saturating_duration_since(t))
+    }
+
+    /// Entfernt einen Benutzer (Verbindung getrennt)
+    pub fn entfernen(&self, user_id: &UserId) {
+        self.inner.remove(user_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test(start_paused = true)]
+    async fn inaktivitaet_folgt_der_uhr() {
+        let tracker = AktivitaetsTracker::neu();
+        let user = UserId::new();
+        assert!(tracker.inaktiv_seit(&user, Instant::now()).is_none());
+
+        tracker.melden(user);
+        tokio::time::advance(Duration::from_secs(90)).await;
+        assert_eq!(
+            tracker.inaktiv_seit(&user, Instant::now()),
+            Some(Duration::from_secs(90))
+        );
+
+        tracker.melden(user);
+        assert_eq!(
+            tracker.inaktiv_seit(&user, Instant::now()),
+            Some(Duration::ZERO)
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn erfassen_ueberschreibt_nicht() {
+        let tracker = AktivitaetsTracker::neu();
+        let user = UserId::new();
+        let start = Instant::now();
+
+        tracker.erfassen_falls_neu(user, start);
+        tokio::time::advance(Duration::from_secs(5)).await;
+        tracker.erfassen_falls_neu(user, Instant::now());
+        assert_eq!(tracker.letzte_aktivitaet(&user), Some(start));
+
+        tracker.entfernen(&user);
+        assert!(tracker.letzte_aktivitaet(&user).is_none());
+    }
+}
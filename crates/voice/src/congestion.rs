@@ -98,6 +98,7 @@ pub struct NetzwerkMetriken {
 // ---------------------------------------------------------------------------
 
 /// Congestion Controller – ueberwacht Netzwerkqualitaet und empfiehlt Bitrate-Anpassungen
+#[derive(Debug, Clone)]
 pub struct CongestionController {
     config: CongestionConfig,
     /// Aktuelle Bitrate-Empfehlung
@@ -161,6 +162,12 @@ impl CongestionController {
         self.verlorene_pakete += 1;
     }
 
+    /// Meldet die Pakete eines ganzen Intervalls auf einmal
+    pub fn pakete_melden(&mut self, gesendet: u64, verloren: u64) {
+        self.gesendete_pakete += gesendet;
+        self.verlorene_pakete += verloren;
+    }
+
     /// Meldet empfangene Bytes (fuer Bitrate-Berechnung)
     pub fn bytes_empfangen(&mut self, bytes: u64) {
         self.empfangene_bytes += bytes;
@@ -296,6 +303,88 @@ impl CongestionController {
     }
 }
 
+// ---------------------------------------------------------------------------
+// Bitrate-Regelung aus Statistik-Berichten
+// ---------------------------------------------------------------------------
+
+/// Geaenderte Bitrate-Empfehlung fuer einen Client
+#[derive(Debug, Clone, PartialEq)]
+pub struct BitrateAnpassung {
+    /// Neue Ziel-Bitrate (kbps)
+    pub bitrate_kbps: u16,
+    /// Auswertung, die zur Aenderung gefuehrt hat
+    pub aktion: CongestionAktion,
+}
+
+/// Bitrate-Regelung eines Clients aus seinen periodischen Statistik-Berichten
+///
+/// Jeder Bericht schliesst ein Messintervall ab: Die kumulierten Uplink-
+/// Zaehler des Servers werden auf das Intervall umgerechnet und vom
+/// [`CongestionController`] ausgewertet. Der Server kennt das Audio-Preset
+/// des Clients nicht, daher regelt er nie ueber die Start-Bitrate hinaus.
+#[derive(Debug, Clone)]
+pub struct BitrateRegelung {
+    controller: CongestionController,
+    /// Uplink-Zaehler (empfangen, erwartet) beim letzten Bericht
+    letzter_stand: (u64, u64),
+}
+
+impl BitrateRegelung {
+    /// Erstellt eine Regelung mit `start_bitrate_kbps` als Obergrenze
+    pub fn neu(start_bitrate_kbps: u16) -> Self {
+        let config = CongestionConfig {
+            max_bitrate_kbps: start_bitrate_kbps,
+            ..Default::default()
+        };
+        Self {
+            controller: CongestionController::mit_config(config, start_bitrate_kbps),
+            letzter_stand: (0, 0),
+        }
+    }
+
+    /// Aktuelle Empfehlung (kbps)
+    pub fn bitrate_kbps(&self) -> u16 {
+        self.controller.aktuelle_bitrate_kbps()
+    }
+
+    /// Wertet einen Bericht aus
+    ///
+    /// `empfangen`/`erwartet` sind die kumulierten Uplink-Zaehler, `rtt_ms`
+    /// die vom Client gemessene RTT. Gibt nur dann eine Anpassung zurueck,
+    /// wenn sich die Empfehlung geaendert hat.
+    pub fn bericht(
+        &mut self,
+        empfangen: u64,
+        erwartet: u64,
+        rtt_ms: Option<u32>,
+    ) -> Option<BitrateAnpassung> {
+        // Zaehler kleiner als zuvor: die Statistik wurde neu begonnen
+        let (vorher_empfangen, vorher_erwartet) = if erwartet < self.letzter_stand.1 {
+            (0, 0)
+        } else {
+            self.letzter_stand
+        };
+        self.letzter_stand = (empfangen, erwartet);
+        let intervall_erwartet = erwartet - vorher_erwartet;
+        let intervall_empfangen = empfangen
+            .saturating_sub(vorher_empfangen)
+            .min(intervall_erwartet);
+        self.controller
+            .pakete_melden(intervall_erwartet, intervall_erwartet - intervall_empfangen);
+        if let Some(rtt_ms) = rtt_ms {
+            self.controller.rtt_aktualisieren(rtt_ms);
+        }
+
+        let vorher = self.controller.aktuelle_bitrate_kbps();
+        let aktion = self.controller.auswerten();
+        let bitrate_kbps = self.controller.aktuelle_bitrate_kbps();
+        (bitrate_kbps != vorher).then_some(BitrateAnpassung {
+            bitrate_kbps,
+            aktion,
+        })
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Tests
 // ---------------------------------------------------------------------------
@@ -464,4 +553,56 @@ mod tests {
             aktion
         );
     }
+
+    #[test]
+    fn regelung_meldet_nur_aenderungen() {
+        let mut regelung = BitrateRegelung::neu(64);
+
+        // Verlustfrei: Empfehlung bleibt bei der Start-Bitrate
+        assert_eq!(regelung.bericht(100, 100, Some(30)), None);
+
+        // 20 % Verlust bei hoher RTT im naechsten Intervall
+        let anpassung = regelung.bericht(180, 200, Some(250)).unwrap();
+        assert!(matches!(
+            anpassung.aktion,
+            CongestionAktion::Kritisch { .. }
+        ));
+        // 64 * 0.75 * 0.75 = 36
+        assert_eq!(anpassung.bitrate_kbps, 36);
+        assert_eq!(regelung.bitrate_kbps(), 36);
+    }
+
+    #[test]
+    fn regelung_steigt_nie_ueber_start_bitrate() {
+        let mut regelung = BitrateRegelung::neu(64);
+        let mut stand = 0;
+        for _ in 0..50 {
+            stand += 100;
+            regelung.bericht(stand, stand, Some(20));
+        }
+        assert_eq!(regelung.bitrate_kbps(), 64);
+
+        // Nach einer Reduzierung erholt sie sich bis zur Start-Bitrate
+        regelung.bericht(stand + 80, stand + 100, Some(20)).unwrap();
+        stand += 100;
+        for _ in 0..50 {
+            stand += 100;
+            regelung.bericht(stand, stand, Some(20));
+        }
+        assert_eq!(regelung.bitrate_kbps(), 64);
+    }
+
+    #[test]
+    fn regelung_verkraftet_zurueckgesetzte_zaehler() {
+        let mut regelung = BitrateRegelung::neu(64);
+        regelung.bericht(1000, 1000, None);
+        // Neue Statistik: 10 von 100 Paketen fehlen
+        let anpassung = regelung.bericht(90, 100, None).unwrap();
+        assert_eq!(
+            anpassung.aktion,
+            CongestionAktion::BitrateReduzieren {
+                neue_bitrate_kbps: 48
+            }
+        );
+    }
 }
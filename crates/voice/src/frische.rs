@@ -0,0 +1,368 @@
+//! Frische-Pruefung – verspaetete Voice-Pakete vor der Weiterleitung verwerfen
+//!
+//! Pakete, die hunderte Millisekunden in der Sende-Queue eines Clients oder im
+//! Netz lagen, verwirft der Jitter-Buffer des Empfaengers ohnehin. Der Server
+//! erkennt sie vorher und spart die Weiterleitung.
+//!
+//! Pro Absender wird der Versatz `ankunft - zeitstempel / takt` gefuehrt. Der
+//! kleinste beobachtete Versatz ist die Basis (schnellste Zustellung); ein
+//! Paket, dessen Versatz die Basis um mehr als die TTL uebersteigt, gilt als
+//! veraltet. Die Pruefung ist O(1) pro Paket und kommt ohne Historie aus.
+//!
+//! ## Diskontinuitaeten
+//! Der Client-Zeitstempel laeuft waehrend einer Stummschaltung nicht weiter,
+//! nach der Pause liegt der Versatz des Stroms dauerhaft hoeher. Damit der
+//! fortgesetzte Strom nicht komplett verworfen wird, verankert die Pruefung
+//! neu bei:
+//! - `SPEAKING_START` (Beginn einer neuen Sprechphase)
+//! - einem Zeitstempel-Sprung um mehr als [`MAX_ZEITSTEMPEL_SPRUNG`]
+//! - veralteten Paketen, die im Takt des Senders eintreffen. Ein verspaeteter
+//!   Burst trifft dagegen gebuendelt ein und baut seinen Rueckstand pro Paket
+//!   um eine Frame-Dauer ab.
+
+use speakeasy_protocol::codec::{FrameSizeMs, OpusConfig};
+use speakeasy_protocol::voice::{VoiceFlags, VoicePacketHeader};
+use std::time::{Duration, Instant};
+
+/// Zeitstempel-Spruenge ueber diese Dauer gelten als neuer Strom
+pub const MAX_ZEITSTEMPEL_SPRUNG: Duration = Duration::from_secs(10);
+
+/// Veraltete Pakete in Folge, die im Takt eintreffen, bevor neu verankert wird
+const RESYNC_PAKETE: u32 = 3;
+
+/// Anteil der Verspaetung, um den die Basis pro frischem Paket nachgefuehrt
+/// wird (als Shift: 1/512). Gleicht Taktabweichungen zwischen Client und
+/// Server aus, ohne dass Jitter die Basis verschiebt.
+const DRIFT_SHIFT: u32 = 9;
+
+/// Erwarteter Takt eines Absenders (aus der vereinbarten Codec-Konfiguration)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Takt {
+    /// Zeitstempel-Ticks pro Sekunde
+    pub ticks_pro_sekunde: u32,
+    /// Dauer eines Frames in Mikrosekunden
+    pub frame_us: i64,
+}
+
+impl Takt {
+    /// Takt zu einer Codec-Konfiguration; ohne Konfiguration 48 kHz / 20 ms
+    ///
+    /// Auch PCMU-Pakete tragen Zeitstempel im 48-kHz-Takt der Pipeline.
+    pub fn aus_codec(config: Option<&OpusConfig>) -> Self {
+        let (rate, frame) = match config {
+            Some(c) => (c.sample_rate as u32, c.frame_size),
+            None => (48_000, FrameSizeMs::Ms20),
+        };
+        Self {
+            ticks_pro_sekunde: rate,
+            frame_us: (frame.as_ms() * 1000.0) as i64,
+        }
+    }
+
+    fn ticks_zu_us(&self, ticks: i64) -> i64 {
+        ticks * 1_000_000 / self.ticks_pro_sekunde as i64
+    }
+}
+
+impl Default for Takt {
+    fn default() -> Self {
+        Self::aus_codec(None)
+    }
+}
+
+/// Ergebnis der Frische-Pruefung eines Pakets
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Frische {
+    /// Paket liegt innerhalb der TTL
+    Frisch,
+    /// Diskontinuitaet erkannt, Paket beginnt einen neuen Bezug
+    NeuVerankert,
+    /// Paket ist um `verspaetung` gegenueber dem erwarteten Takt verspaetet
+    Veraltet { verspaetung: Duration },
+}
+
+impl Frische {
+    /// Soll das Paket weitergeleitet werden?
+    pub fn weiterleiten(&self) -> bool {
+        !matches!(self, Frische::Veraltet { .. })
+    }
+}
+
+/// Bezugspunkt fuer den Versatz eines Absenders
+#[derive(Debug, Clone)]
+struct Anker {
+    /// Ankunftszeit, ab der der Versatz gemessen wird
+    start: Instant,
+    /// Hoechster bisher gesehener Zeitstempel (roh)
+    zeitstempel: u32,
+    /// Hoechster Zeitstempel relativ zum Anker (ueber Wrap-Arounds hinweg)
+    erweitert: i64,
+    /// Kleinster Versatz in Mikrosekunden
+    basis_us: i64,
+    /// Versatz des letzten veralteten Pakets und Laenge der Serie im Takt
+    serie: Option<(i64, u32)>,
+}
+
+/// Verfolgt die Ankunftskadenz eines Absenders
+#[derive(Debug, Clone, Default)]
+pub struct FrischePruefung {
+    anker: Option<Anker>,
+}
+
+impl FrischePruefung {
+    /// Erstellt eine Pruefung ohne Bezugspunkt (erstes Paket verankert)
+    pub fn neu() -> Self {
+        Self::default()
+    }
+
+    /// Verwirft den Bezugspunkt (z.B. wenn der Client seinen Strom neu beginnt)
+    pub fn zuruecksetzen(&mut self) {
+        self.anker = None;
+    }
+
+    /// Prueft ein Paket gegen den erwarteten Takt und die TTL
+    pub fn pruefen(
+        &mut self,
+        header: &VoicePacketHeader,
+        ankunft: Instant,
+        takt: Takt,
+        ttl: Duration,
+    ) -> Frische {
+        let max_sprung = takt.ticks_pro_sekunde as i64 * MAX_ZEITSTEMPEL_SPRUNG.as_secs() as i64;
+        let anker = match self.anker.as_mut() {
+            Some(anker) if !header.hat_flag(VoiceFlags::SPEAKING_START) => anker,
+            _ => return self.verankern(header, ankunft),
+        };
+
+        // Differenz modulo 2^32: uebersteht den Wrap-Around des Zeitstempels
+        let diff = header.timestamp.wrapping_sub(anker.zeitstempel) as i32 as i64;
+        if diff.abs() > max_sprung {
+            return self.verankern(header, ankunft);
+        }
+        let erweitert = anker.erweitert + diff;
+        if diff > 0 {
+            anker.zeitstempel = header.timestamp;
+            anker.erweitert = erweitert;
+        }
+
+        let ankunft_us = ankunft.saturating_duration_since(anker.start).as_micros() as i64;
+        let versatz = ankunft_us - takt.ticks_zu_us(erweitert);
+        anker.basis_us = anker.basis_us.min(versatz);
+        let verspaetung = versatz - anker.basis_us;
+
+        if verspaetung <= ttl.as_micros() as i64 {
+            anker.basis_us += verspaetung >> DRIFT_SHIFT;
+            anker.serie = None;
+            return Frische::Frisch;
+        }
+
+        // Bleibt der Versatz stabil, laeuft der Strom im Takt weiter: das ist
+        // ein fortgesetzter Strom nach einer Pause, kein verspaeteter Burst
+        let im_takt = match anker.serie {
+            Some((letzter, anzahl)) if (versatz - letzter).abs() < takt.frame_us / 2 => anzahl + 1,
+            _ => 1,
+        };
+        if im_takt >= RESYNC_PAKETE {
+            anker.basis_us = versatz;
+            anker.serie = None;
+            return Frische::NeuVerankert;
+        }
+        anker.serie = Some((versatz, im_takt));
+        Frische::Veraltet {
+            verspaetung: Duration::from_micros(verspaetung as u64),
+        }
+    }
+
+    fn verankern(&mut self, header: &VoicePacketHeader, ankunft: Instant) -> Frische {
+        self.anker = Some(Anker {
+            start: ankunft,
+            zeitstempel: header.timestamp,
+            erweitert: 0,
+            basis_us: 0,
+            serie: None,
+        });
+        Frische::NeuVerankert
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use speakeasy_protocol::voice::PacketType;
+
+    const TTL: Duration = Duration::from_millis(500);
+
+    fn header(seq: u32, zeitstempel: u32) -> VoicePacketHeader {
+        VoicePacketHeader::new(PacketType::Audio, 0, seq, zeitstempel, 0xCAFE)
+    }
+
+    fn ms(wert: u64) -> Duration {
+        Duration::from_millis(wert)
+    }
+
+    /// Sendet die Pakete `seq` im 20-ms-Takt, Ankunft um `verzoegerung` versetzt
+    fn strom(
+        pruefung: &mut FrischePruefung,
+        t0: Instant,
+        seq: std::ops::Range<u32>,
+        verzoegerung: Duration,
+    ) -> Vec<Frische> {
+        seq.map(|s| {
+            let ankunft = t0 + ms(s as u64 * 20) + verzoegerung;
+            pruefung.pruefen(&header(s, s * 960), ankunft, Takt::default(), TTL)
+        })
+        .collect()
+    }
+
+    #[test]
+    fn takt_aus_codec() {
+        assert_eq!(Takt::default().ticks_pro_sekunde, 48_000);
+        assert_eq!(Takt::default().frame_us, 20_000);
+
+        let mut config = speakeasy_protocol::codec::AudioPreset::Speech.config();
+        config.frame_size = FrameSizeMs::Ms10;
+        let takt = Takt::aus_codec(Some(&config));
+        assert_eq!(takt.ticks_pro_sekunde, 16_000);
+        assert_eq!(takt.frame_us, 10_000);
+    }
+
+    #[test]
+    fn puenktlicher_strom_ist_frisch() {
+        let mut pruefung = FrischePruefung::neu();
+        let t0 = Instant::now();
+        let ergebnisse = strom(&mut pruefung, t0, 0..100, Duration::ZERO);
+        assert_eq!(ergebnisse[0], Frische::NeuVerankert);
+        assert!(ergebnisse[1..].iter().all(|f| *f == Frische::Frisch));
+    }
+
+    #[test]
+    fn verspaeteter_burst_wird_verworfen_live_strom_laeuft_weiter() {
+        let mut pruefung = FrischePruefung::neu();
+        let t0 = Instant::now();
+        strom(&mut pruefung, t0, 0..50, Duration::ZERO);
+
+        // Pakete 50..90 haengen in der Sende-Queue und kommen gesammelt bei
+        // t = 1.8 s an (Paket 50 wurde bei 1.0 s erzeugt: 800 ms zu spaet)
+        let burst_ankunft = t0 + ms(1800);
+        let burst: Vec<Frische> = (50..90)
+            .map(|s| pruefung.pruefen(&header(s, s * 960), burst_ankunft, Takt::default(), TTL))
+            .collect();
+        let verworfen = burst.iter().filter(|f| !f.weiterleiten()).count();
+        // Alles mit mehr als 500 ms Rueckstand (Pakete 50..64) wird verworfen
+        assert_eq!(verworfen, 15);
+        assert!(burst[..15]
+            .iter()
+            .all(|f| matches!(f, Frische::Veraltet { verspaetung } if *verspaetung > TTL)));
+        assert!(!burst.contains(&Frische::NeuVerankert));
+
+        // Der Live-Strom laeuft danach puenktlich weiter
+        let live = strom(&mut pruefung, t0, 90..150, Duration::ZERO);
+        assert!(live.iter().all(|f| *f == Frische::Frisch));
+    }
+
+    #[test]
+    fn jitter_unter_ttl_wird_nicht_verworfen() {
+        let mut pruefung = FrischePruefung::neu();
+        let t0 = Instant::now();
+        for s in 0..200u32 {
+            let jitter = ms((s as u64 * 37) % 120);
+            let ankunft = t0 + ms(s as u64 * 20) + jitter;
+            let frische = pruefung.pruefen(&header(s, s * 960), ankunft, Takt::default(), TTL);
+            assert!(frische.weiterleiten(), "Paket {s} verworfen");
+        }
+    }
+
+    #[test]
+    fn zeitstempel_wrap_around() {
+        let mut pruefung = FrischePruefung::neu();
+        let t0 = Instant::now();
+        let start = u32::MAX - 960 * 10;
+        for i in 0..40u32 {
+            let zeitstempel = start.wrapping_add(i * 960);
+            let ankunft = t0 + ms(i as u64 * 20);
+            let frische = pruefung.pruefen(&header(i, zeitstempel), ankunft, Takt::default(), TTL);
+            assert!(frische.weiterleiten(), "Paket {i} nach Wrap verworfen");
+        }
+
+        // Auch nach dem Wrap wird ein verspaetetes Paket erkannt
+        let zeitstempel = start.wrapping_add(40 * 960);
+        let frische = pruefung.pruefen(
+            &header(40, zeitstempel),
+            t0 + ms(1600),
+            Takt::default(),
+            TTL,
+        );
+        assert!(!frische.weiterleiten());
+    }
+
+    #[test]
+    fn pause_mit_speaking_start_verankert_neu() {
+        let mut pruefung = FrischePruefung::neu();
+        let t0 = Instant::now();
+        strom(&mut pruefung, t0, 0..50, Duration::ZERO);
+
+        // Stummgeschaltet: Zeitstempel steht, 3 s spaeter geht es weiter
+        let mut kopf = header(50, 50 * 960);
+        kopf.flags |= VoiceFlags::SPEAKING_START;
+        let pause = ms(3000);
+        let erstes = pruefung.pruefen(&kopf, t0 + ms(1000) + pause, Takt::default(), TTL);
+        assert_eq!(erstes, Frische::NeuVerankert);
+
+        let weiter = strom(&mut pruefung, t0, 51..100, pause);
+        assert!(weiter.iter().all(|f| *f == Frische::Frisch));
+    }
+
+    #[test]
+    fn pause_ohne_flag_verwirft_nur_wenige_pakete() {
+        let mut pruefung = FrischePruefung::neu();
+        let t0 = Instant::now();
+        strom(&mut pruefung, t0, 0..50, Duration::ZERO);
+
+        // Fortsetzung im Takt, aber 3 s hinter dem Zeitstempel
+        let weiter = strom(&mut pruefung, t0, 50..100, ms(3000));
+        let verworfen = weiter.iter().filter(|f| !f.weiterleiten()).count();
+        assert_eq!(verworfen, RESYNC_PAKETE as usize - 1);
+        assert_eq!(weiter[RESYNC_PAKETE as usize - 1], Frische::NeuVerankert);
+        assert!(weiter[RESYNC_PAKETE as usize..]
+            .iter()
+            .all(|f| *f == Frische::Frisch));
+    }
+
+    #[test]
+    fn zeitstempel_sprung_beginnt_neuen_strom() {
+        let mut pruefung = FrischePruefung::neu();
+        let t0 = Instant::now();
+        strom(&mut pruefung, t0, 0..50, Duration::ZERO);
+
+        // Neuer Strom mit anderem Zeitstempel-Ursprung (z.B. nach Neustart)
+        let ursprung = 0x8000_0000;
+        let neu = pruefung.pruefen(&header(0, ursprung), t0 + ms(5000), Takt::default(), TTL);
+        assert_eq!(neu, Frische::NeuVerankert);
+        let kopf = header(1, ursprung + 960);
+        let frisch = pruefung.pruefen(&kopf, t0 + ms(5020), Takt::default(), TTL);
+        assert_eq!(frisch, Frische::Frisch);
+    }
+
+    #[test]
+    fn langsame_taktabweichung_wird_nachgefuehrt() {
+        let mut pruefung = FrischePruefung::neu();
+        let t0 = Instant::now();
+        // Client-Takt 0.1 % zu langsam: nach 30 min 1.8 s Rueckstand
+        for s in 0..90_000u32 {
+            let ankunft = t0 + Duration::from_micros(s as u64 * 20_020);
+            let frische = pruefung.pruefen(&header(s, s * 960), ankunft, Takt::default(), TTL);
+            assert_eq!(
+                frische,
+                if s == 0 {
+                    Frische::NeuVerankert
+                } else {
+                    Frische::Frisch
+                }
+            );
+        }
+    }
+}
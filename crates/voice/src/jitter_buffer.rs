@@ -5,6 +5,14 @@
 //! - **Adaptiv**: passt die Buffer-Groesse dynamisch an gemessenen Jitter an
 //! - **Fixed**: konstante Buffer-Groesse (deterministische Latenz)
 //!
+//! ## Latenzbudget
+//! Ohne Budget waechst das adaptive Ziel bei anhaltendem Jitter bis
+//! `max_pakete` (50 Pakete = eine Sekunde). Mit `budget_pakete` bleibt das Ziel
+//! unabhaengig vom Jitter darunter, und der Buffer haelt nie mehr Pakete als
+//! das Budget: ueberzaehlige aelteste Pakete werden verworfen
+//! (`budget_verworfen`), die Luecke verdeckt der Decoder per PLC. Verlangt der
+//! Jitter dauerhaft mehr, meldet [`AdaptiveJitterBuffer::ueber_budget`] das.
+//!
 //! ## Performance-Eigenschaften
 //! - O(log n) Einf??gen (BTreeMap nach Sequence sortiert)
 //! - O(1) Entnahme des aeltesten Pakets
@@ -37,6 +45,8 @@ pub struct JitterBufferConfig {
     pub min_pakete: usize,
     /// Fenstergroesse fuer Jitter-Messung (letzte N Interarrivals)
     pub jitter_fenster: usize,
+    /// Latenzbudget: hoechstens so viele Pakete Verzoegerung (`None` = nur `max_pakete`)
+    pub budget_pakete: Option<usize>,
 }
 
 impl Default for JitterBufferConfig {
@@ -46,6 +56,7 @@ impl Default for JitterBufferConfig {
             max_pakete: 50,
             min_pakete: 2,
             jitter_fenster: 16,
+            budget_pakete: None,
         }
     }
 }
@@ -73,6 +84,8 @@ pub struct JitterBufferStatistik {
     pub fuellstand: usize,
     /// Aktuelle Ziel-Buffer-Groesse (adaptiv angepasst)
     pub ziel_groesse: usize,
+    /// Wegen des Latenzbudgets verworfene Pakete
+    pub budget_verworfen: u64,
 }
 
 // ---------------------------------------------------------------------------
@@ -105,12 +118,15 @@ pub struct AdaptiveJitterBuffer {
     jitter_n: u64,
     /// Aktuelle Ziel-Buffer-Groesse (adaptiv)
     ziel_groesse: usize,
+    /// Geglaetteter Bedarf in Paketen, vor der Begrenzung (`None` = noch nicht gemessen)
+    bedarf_glatt: Option<f64>,
 }
 
 impl AdaptiveJitterBuffer {
     /// Erstellt einen neuen Jitter Buffer mit gegebener Konfiguration
     pub fn neu(config: JitterBufferConfig) -> Self {
         let ziel = config.max_pakete / 2; // Startwert: Haelfte des Maximums
+        let ziel = config.budget_pakete.map_or(ziel, |budget| ziel.min(budget));
         let fenster = config.jitter_fenster;
         Self {
             ziel_groesse: ziel.max(config.min_pakete),
@@ -125,6 +141,7 @@ impl AdaptiveJitterBuffer {
             jitter_mittel: 0.0,
             jitter_m2: 0.0,
             jitter_n: 0,
+            bedarf_glatt: None,
         }
     }
 
@@ -140,7 +157,17 @@ impl AdaptiveJitterBuffer {
         let seq = paket.header.sequence;
         self.statistik.empfangen += 1;
 
-        // Duplikat-Erkennung
+        // Duplikat im Buffer selbst
+        if self.pakete.contains_key(&seq) {
+            self.statistik.duplikate += 1;
+            return;
+        }
+
+        // Jitter messen (basierend auf RTP-Timestamp), auch fuer Pakete, die
+        // zu spaet kommen: gerade sie zeigen, wie viel Puffer fehlt
+        self.jitter_messen(paket.header.timestamp);
+
+        // Duplikat-Erkennung (bzw. zu spaet fuer die Wiedergabe)
         if let Some(letzte) = self.letzte_abgespielt {
             if self.ist_sequence_alt(seq, letzte) {
                 self.statistik.duplikate += 1;
@@ -149,12 +176,6 @@ impl AdaptiveJitterBuffer {
             }
         }
 
-        // Duplikat im Buffer selbst
-        if self.pakete.contains_key(&seq) {
-            self.statistik.duplikate += 1;
-            return;
-        }
-
         // Out-of-Order Erkennung
         if let Some(naechste) = self.naechste_seq {
             if self.ist_sequence_alt(seq, naechste.wrapping_sub(1)) {
@@ -163,18 +184,24 @@ impl AdaptiveJitterBuffer {
             }
         }
 
-        // Jitter messen (basierend auf RTP-Timestamp)
-        self.jitter_messen(paket.header.timestamp);
-
         // Paket einf??gen
         self.pakete.insert(seq, paket);
 
-        // Buffer-Ueberlauf: aeltestes Paket verwerfen
-        if self.pakete.len() > self.config.max_pakete {
+        // Buffer-Ueberlauf bzw. Budget ueberschritten: aeltestes Paket verwerfen
+        let budget_greift = self
+            .config
+            .budget_pakete
+            .is_some_and(|budget| budget <= self.config.max_pakete);
+        if self.pakete.len() > self.grenze() {
             if let Some((&aelteste_seq, _)) = self.pakete.iter().next() {
                 self.pakete.remove(&aelteste_seq);
-                self.statistik.verloren += 1;
-                tracing::warn!(sequence = aelteste_seq, "Buffer-Ueberlauf: Paket verworfen");
+                if budget_greift {
+                    self.statistik.budget_verworfen += 1;
+                    tracing::debug!(sequence = aelteste_seq, "Latenzbudget: Paket verworfen");
+                } else {
+                    self.statistik.verloren += 1;
+                    tracing::warn!(sequence = aelteste_seq, "Buffer-Ueberlauf: Paket verworfen");
+                }
             }
         }
 
@@ -246,6 +273,11 @@ impl AdaptiveJitterBuffer {
         varianz.sqrt() as u32
     }
 
+    /// Sequenznummer des naechsten Pakets, ohne es zu entnehmen
+    pub fn naechste_sequenz(&self) -> Option<u32> {
+        self.pakete.keys().next().copied()
+    }
+
     /// Gibt den aktuellen Fuellstand zurueck
     pub fn fuellstand(&self) -> usize {
         self.pakete.len()
@@ -256,6 +288,17 @@ impl AdaptiveJitterBuffer {
         self.ziel_groesse
     }
 
+    /// Verlangt der gemessene Jitter mehr Puffer, als das Latenzbudget erlaubt?
+    ///
+    /// Vergleicht den geglaetteten Bedarf, einzelne Ausreisser schlagen also
+    /// nicht durch. Ohne Budget immer `false`.
+    pub fn ueber_budget(&self) -> bool {
+        match (self.config.budget_pakete, self.bedarf_glatt) {
+            (Some(budget), Some(bedarf)) => bedarf.round() as usize > budget,
+            _ => false,
+        }
+    }
+
     // -----------------------------------------------------------------------
     // Interne Hilfsfunktionen
     // -----------------------------------------------------------------------
@@ -272,7 +315,8 @@ impl AdaptiveJitterBuffer {
     /// Misst den Jitter mit dem Welford-Online-Algorithmus (numerisch stabil)
     fn jitter_messen(&mut self, timestamp: u32) {
         if let Some(letzter) = self.letzter_timestamp {
-            let interarrival = timestamp.wrapping_sub(letzter) as i64;
+            // Vorzeichenbehaftet: umsortierte Pakete ergeben negative Abstaende
+            let interarrival = timestamp.wrapping_sub(letzter) as i32 as i64;
             self.interarrivals[self.interarrival_idx] = interarrival;
             self.interarrival_idx = (self.interarrival_idx + 1) % self.interarrivals.len();
 
@@ -288,18 +332,36 @@ impl AdaptiveJitterBuffer {
         self.letzter_timestamp = Some(timestamp);
     }
 
+    /// Hoechstens gepufferte Pakete: `max_pakete`, mit Budget darunter
+    fn grenze(&self) -> usize {
+        let max = self.config.max_pakete;
+        self.config
+            .budget_pakete
+            .map_or(max, |budget| budget.max(1).min(max))
+    }
+
     /// Passt die Ziel-Buffer-Groesse basierend auf gemessenem Jitter an
     ///
-    /// Heuristik: Ziel = max(min_pakete, jitter_stddev / typische_frame_dauer + 2)
+    /// Heuristik: Bedarf = jitter_stddev / typische_frame_dauer + 2, geglaettet
+    /// (steigt schnell, faellt langsam, damit das Ziel nicht pendelt);
+    /// Ziel = Bedarf begrenzt auf min_pakete..=min(max_pakete, Budget)
     /// Bei 20ms Frames und 48kHz: 1 Frame = 960 Ticks
     fn ziel_groesse_anpassen(&mut self) {
         const TICKS_PRO_FRAME: u32 = 960; // 20ms bei 48kHz
+        const GLAETTUNG_STEIGEND: f64 = 0.5;
+        const GLAETTUNG_FALLEND: f64 = 1.0 / 16.0;
         let jitter = self.jitter_ticks();
         // Benoetigt so viele Frames wie der Jitter gross ist, plus 2 Sicherheitspuffer
-        let benoetigt = (jitter / TICKS_PRO_FRAME) as usize + 2;
-        self.ziel_groesse = benoetigt
+        let benoetigt = ((jitter / TICKS_PRO_FRAME) as usize + 2) as f64;
+        let bedarf = match self.bedarf_glatt {
+            None => benoetigt,
+            Some(glatt) if benoetigt > glatt => glatt + (benoetigt - glatt) * GLAETTUNG_STEIGEND,
+            Some(glatt) => glatt + (benoetigt - glatt) * GLAETTUNG_FALLEND,
+        };
+        self.bedarf_glatt = Some(bedarf);
+        self.ziel_groesse = (bedarf.round() as usize)
             .max(self.config.min_pakete)
-            .min(self.config.max_pakete);
+            .min(self.grenze());
     }
 }
 
@@ -326,6 +388,7 @@ mod tests {
             max_pakete: 10,
             min_pakete: 0,
             jitter_fenster: 8,
+            budget_pakete: None,
         };
         let mut buf = AdaptiveJitterBuffer::neu(config);
 
@@ -351,6 +414,7 @@ mod tests {
             max_pakete: 10,
             min_pakete: 0,
             jitter_fenster: 8,
+            budget_pakete: None,
         };
         let mut buf = AdaptiveJitterBuffer::neu(config);
 
@@ -362,6 +426,7 @@ mod tests {
         buf.push(make_paket(3, 2880));
 
         // Muessen sortiert herauskommen
+        assert_eq!(buf.naechste_sequenz(), Some(0));
         let mut seqs = Vec::new();
         while let Some(p) = buf.pop() {
             seqs.push(p.header.sequence);
@@ -380,6 +445,7 @@ mod tests {
             max_pakete: 10,
             min_pakete: 0,
             jitter_fenster: 8,
+            budget_pakete: None,
         };
         let mut buf = AdaptiveJitterBuffer::neu(config);
 
@@ -398,6 +464,7 @@ mod tests {
             max_pakete: 20,
             min_pakete: 0,
             jitter_fenster: 8,
+            budget_pakete: None,
         };
         let mut buf = AdaptiveJitterBuffer::neu(config);
 
@@ -422,6 +489,7 @@ mod tests {
             max_pakete: 3,
             min_pakete: 0,
             jitter_fenster: 4,
+            budget_pakete: None,
         };
         let mut buf = AdaptiveJitterBuffer::neu(config);
 
@@ -445,6 +513,7 @@ mod tests {
             max_pakete: 20,
             min_pakete: 2,
             jitter_fenster: 8,
+            budget_pakete: None,
         };
         let mut buf = AdaptiveJitterBuffer::neu(config);
 
@@ -465,6 +534,7 @@ mod tests {
             max_pakete: 10,
             min_pakete: 0,
             jitter_fenster: 4,
+            budget_pakete: None,
         };
         let mut buf = AdaptiveJitterBuffer::neu(config);
 
@@ -476,4 +546,99 @@ mod tests {
 
         assert_eq!(buf.fuellstand(), 3);
     }
+
+    #[test]
+    fn jitter_buffer_umsortierung_ohne_ueberlauf() {
+        let mut buf = AdaptiveJitterBuffer::standard();
+        // Jedes zweite Paket kommt einen Frame zu frueh
+        for i in 0..10u32 {
+            let seq = if i % 2 == 0 { i + 1 } else { i - 1 };
+            buf.push(make_paket(seq, seq * 960));
+        }
+
+        // Negative Abstaende duerfen nicht als riesige u32-Werte zaehlen
+        assert!(buf.jitter_ticks() < 3 * 960, "{}", buf.jitter_ticks());
+        assert!(buf.ziel_groesse() < 10, "{}", buf.ziel_groesse());
+    }
+
+    fn budget_config(modus: JitterBufferModus, budget: Option<usize>) -> JitterBufferConfig {
+        JitterBufferConfig {
+            modus,
+            max_pakete: 50,
+            min_pakete: 0,
+            jitter_fenster: 16,
+            budget_pakete: budget,
+        }
+    }
+
+    /// Bloecke von `versatz` Paketen kommen vertauscht an (je Paket ein Pop,
+    /// sobald `vorlauf` Pakete eingereiht sind); gibt die Ziele nach jedem Push zurueck
+    fn mit_jitter(buf: &mut AdaptiveJitterBuffer, versatz: u32, vorlauf: u32) -> Vec<usize> {
+        let mut ziele = Vec::new();
+        for i in 0..200u32 {
+            let seq = if (i / versatz).is_multiple_of(2) {
+                i + versatz
+            } else {
+                i - versatz
+            };
+            buf.push(make_paket(seq, seq * 960));
+            ziele.push(buf.ziel_groesse());
+            if i >= vorlauf {
+                buf.pop();
+            }
+        }
+        ziele
+    }
+
+    #[test]
+    fn latenzbudget_begrenzt_ziel_bei_starkem_jitter() {
+        let mut ohne = AdaptiveJitterBuffer::neu(budget_config(JitterBufferModus::Adaptiv, None));
+        mit_jitter(&mut ohne, 8, 10);
+        assert!(ohne.ziel_groesse() > 4, "{}", ohne.ziel_groesse());
+
+        let mut buf = AdaptiveJitterBuffer::neu(budget_config(JitterBufferModus::Adaptiv, Some(4)));
+        let ziele = mit_jitter(&mut buf, 8, 10);
+        assert!(ziele.iter().all(|&z| z <= 4), "{ziele:?}");
+        assert!(buf.ueber_budget());
+        // Mehr als das Budget wird nie gepuffert, der Rest verworfen und gezaehlt
+        assert!(buf.fuellstand() <= 4);
+        assert!(buf.statistik().budget_verworfen > 0);
+    }
+
+    #[test]
+    fn geringer_jitter_bleibt_im_budget() {
+        let mut buf = AdaptiveJitterBuffer::neu(budget_config(JitterBufferModus::Adaptiv, Some(8)));
+        mit_jitter(&mut buf, 2, 4);
+
+        assert!(!buf.ueber_budget());
+        assert_eq!(buf.ziel_groesse(), 4);
+        assert_eq!(buf.statistik().budget_verworfen, 0);
+    }
+
+    #[test]
+    fn budget_verwirft_aelteste_pakete() {
+        let mut buf = AdaptiveJitterBuffer::neu(budget_config(JitterBufferModus::Fixed, Some(3)));
+        for i in 0..10u32 {
+            buf.push(make_paket(i, i * 960));
+        }
+
+        assert_eq!(buf.fuellstand(), 3);
+        assert_eq!(buf.statistik().budget_verworfen, 7);
+        assert_eq!(buf.statistik().verloren, 0);
+        assert_eq!(buf.pop().unwrap().header.sequence, 7);
+        // Kein Jitter: das Budget reicht, es wird nur ueberzaehliges verworfen
+        assert!(!buf.ueber_budget());
+    }
+
+    #[test]
+    fn ziel_pendelt_nicht() {
+        let mut buf = AdaptiveJitterBuffer::neu(budget_config(JitterBufferModus::Adaptiv, None));
+        // Ohne Vorlauf kommt jeder zweite Block zu spaet, ungeglaettet springt
+        // das Ziel dabei zwischen 3 und 4 Paketen
+        let ziele = mit_jitter(&mut buf, 5, 0);
+
+        // Nach dem Einschwingen bleibt das Ziel stehen
+        let wechsel = ziele[50..].windows(2).filter(|w| w[0] != w[1]).count();
+        assert!(wechsel <= 1, "{ziele:?}");
+    }
 }
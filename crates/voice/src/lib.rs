@@ -8,17 +8,34 @@
 //! - [`jitter_buffer`] – Adaptiver Jitter Buffer
 //! - [`congestion`] – Congestion Controller mit Bitrate-Adaptation
 //! - [`state`] – In-Memory Voice-State aller Sessions
-//! - [`telemetry`] – Quality-Telemetrie und Metriken
+//! - [`telemetry`] – Quality-Telemetrie, Metriken und MOS-Schaetzung
 //! - [`plc`] – Packet Loss Concealment
+//! - [`aktivitaet`] – Letzte Benutzeraktivitaet (AFK-Erkennung)
+//! - [`frische`] – Verwerfen verspaeteter Pakete (TTL)
+//! - [`sprecher`] – Aktive Sprecher mit Nachlauf und gedrosselten Meldungen
+//! - [`notfall`] – Notfall-Stummschaltung ganzer Kanaele
+//! - [`ping`] – Ping-Reflektor fuer Latenzmessungen ohne Anmeldung
+//! - [`soundboard`] – Einspeisen kurzer Clips in einen Kanal
 
+pub mod aktivitaet;
 pub mod congestion;
+pub mod frische;
 pub mod jitter_buffer;
+pub mod notfall;
+pub mod ping;
 pub mod plc;
 pub mod router;
+pub mod soundboard;
+pub mod sprecher;
 pub mod state;
 pub mod telemetry;
 pub mod udp;
 
-pub use router::ChannelRouter;
-pub use state::VoiceState;
+pub use aktivitaet::AktivitaetsTracker;
+pub use notfall::NotfallStumm;
+pub use ping::{PingLimits, PingReflektor};
+pub use router::{ChannelRouter, Resequenzierung};
+pub use soundboard::Soundboard;
+pub use sprecher::{SprecherDrossel, SprecherTracker};
+pub use state::{HelloErgebnis, VoiceState};
 pub use udp::VoiceServer;
@@ -0,0 +1,112 @@
+//! Notfall-Stummschaltung – Kanal serverseitig stumm schalten
+//!
+//! Moderatoren koennen einen Kanal im Notfall (Raid, Musik-Spam) mit einem
+//! Klick stumm schalten. Solange die Stummschaltung aktiv ist, leitet der
+//! [`ChannelRouter`](crate::router::ChannelRouter) keine Pakete von
+//! Absendern in diesem Kanal weiter, ausser von ausgenommenen Benutzern
+//! (Berechtigung `b_channel_emergency_mute_bypass`).
+//!
+//! Der Zustand liegt nur im Speicher und geht beim Neustart verloren. Die
+//! Pruefung erfolgt pro Paket, eine Aenderung wirkt also ab dem naechsten
+//! Paket.
+
+use dashmap::DashMap;
+use speakeasy_core::types::{ChannelId, UserId};
+use std::collections::HashSet;
+use std::sync::Arc;
+
+/// Stumm geschaltete Kanaele mit ihren Ausnahmen (Clone teilt den Zustand)
+#[derive(Clone, Default)]
+pub struct NotfallStumm {
+    /// Kanal -> ausgenommene Benutzer
+    kanaele: Arc<DashMap<ChannelId, HashSet<UserId>>>,
+}
+
+impl NotfallStumm {
+    /// Erstellt einen Zustand ohne stumm geschaltete Kanaele
+    pub fn neu() -> Self {
+        Self::default()
+    }
+
+    /// Schaltet einen Kanal stumm; ersetzt die bisherigen Ausnahmen
+    pub fn aktivieren(&self, kanal_id: ChannelId, ausgenommen: impl IntoIterator<Item = UserId>) {
+        self.kanaele
+            .insert(kanal_id, ausgenommen.into_iter().collect());
+    }
+
+    /// Hebt die Stummschaltung auf (`false` wenn sie nicht aktiv war)
+    pub fn deaktivieren(&self, kanal_id: &ChannelId) -> bool {
+        self.kanaele.remove(kanal_id).is_some()
+    }
+
+    /// Prueft ob ein Kanal stumm geschaltet ist
+    pub fn ist_aktiv(&self, kanal_id: &ChannelId) -> bool {
+        self.kanaele.contains_key(kanal_id)
+    }
+
+    /// Nimmt einen Benutzer aus (`true`) oder hebt die Ausnahme auf
+    ///
+    /// Ohne aktive Stummschaltung im Kanal wirkungslos.
+    pub fn ausnahme_setzen(&self, kanal_id: &ChannelId, user_id: UserId, ausgenommen: bool) {
+        if let Some(mut ausnahmen) = self.kanaele.get_mut(kanal_id) {
+            if ausgenommen {
+                ausnahmen.insert(user_id);
+            } else {
+                ausnahmen.remove(&user_id);
+            }
+        }
+    }
+
+    /// Ausgenommene Benutzer eines stumm geschalteten Kanals (sortiert)
+    pub fn ausgenommen(&self, kanal_id: &ChannelId) -> Option<Vec<UserId>> {
+        self.kanaele.get(kanal_id).map(|ausnahmen| {
+            let mut liste: Vec<UserId> = ausnahmen.iter().copied().collect();
+            liste.sort_by_key(|id| id.inner());
+            liste
+        })
+    }
+
+    /// Prueft ob Pakete eines Benutzers im Kanal verworfen werden muessen
+    pub fn unterdrueckt(&self, kanal_id: &ChannelId, user_id: &UserId) -> bool {
+        self.kanaele
+            .get(kanal_id)
+            .is_some_and(|ausnahmen| !ausnahmen.contains(user_id))
+    }
+
+    /// Alle stumm geschalteten Kanaele
+    pub fn aktive_kanaele(&self) -> Vec<ChannelId> {
+        self.kanaele.iter().map(|e| *e.key()).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ausnahmen_gelten_nur_bei_aktiver_stummschaltung() {
+        let notfall = NotfallStumm::neu();
+        let kanal = ChannelId::new();
+        let moderator = UserId::new();
+        let gast = UserId::new();
+
+        assert!(!notfall.unterdrueckt(&kanal, &gast));
+        notfall.ausnahme_setzen(&kanal, gast, true);
+        assert!(notfall.ausgenommen(&kanal).is_none());
+
+        notfall.aktivieren(kanal, [moderator]);
+        assert!(notfall.unterdrueckt(&kanal, &gast));
+        assert!(!notfall.unterdrueckt(&kanal, &moderator));
+        assert!(!notfall.unterdrueckt(&ChannelId::new(), &gast));
+
+        notfall.ausnahme_setzen(&kanal, gast, true);
+        assert!(!notfall.unterdrueckt(&kanal, &gast));
+        notfall.ausnahme_setzen(&kanal, moderator, false);
+        assert!(notfall.unterdrueckt(&kanal, &moderator));
+
+        assert!(notfall.deaktivieren(&kanal));
+        assert!(!notfall.deaktivieren(&kanal));
+        assert!(!notfall.unterdrueckt(&kanal, &moderator));
+        assert!(notfall.aktive_kanaele().is_empty());
+    }
+}
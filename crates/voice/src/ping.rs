@@ -0,0 +1,275 @@
+//! Ping-Reflektor – Latenzmessung ohne Anmeldung
+//!
+//! Beantwortet [`PingPaket`]-Anfragen auf dem Voice-Port mit der Serverzeit.
+//! Clients vergleichen damit mehrere Server, ohne sich zu verbinden. Der
+//! Reflektor legt keinen Session- oder SSRC-Zustand an und leitet nichts
+//! weiter.
+//!
+//! ## Absicherung
+//! - Nur Anfragen mit genau [`PING_GROESSE`] Bytes: die Antwort ist nie
+//!   groesser als die Anfrage (kein Verstaerker fuer gefaelschte Absender)
+//! - Antworten werden nie beantwortet (keine Ping-Schleifen zwischen Servern)
+//! - Token-Bucket pro Quell-IP; die Anzahl verfolgter IPs ist begrenzt
+//!
+//! Verwendet `tokio::time::Instant`, damit Tests die Uhr steuern koennen.
+
+use parking_lot::Mutex;
+use speakeasy_protocol::voice::{PingPaket, PING_GROESSE};
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::time::Instant;
+
+/// Grenzen des Ping-Reflektors
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PingLimits {
+    /// Nachgefuellte Antworten pro Sekunde und Quell-IP
+    pub pro_sekunde: f64,
+    /// Antworten, die eine Quell-IP auf einmal abrufen darf
+    pub burst: u32,
+    /// Hoechstzahl gleichzeitig verfolgter Quell-IPs
+    pub max_quellen: usize,
+}
+
+impl Default for PingLimits {
+    fn default() -> Self {
+        Self {
+            pro_sekunde: 5.0,
+            burst: 10,
+            max_quellen: 4096,
+        }
+    }
+}
+
+/// Token-Bucket einer Quell-IP
+#[derive(Debug, Clone, Copy)]
+struct Eimer {
+    tokens: f64,
+    stand: Instant,
+}
+
+impl Eimer {
+    fn auffuellen(&mut self, jetzt: Instant, limits: &PingLimits) {
+        let vergangen = jetzt.saturating_duration_since(self.stand).as_secs_f64();
+        self.tokens = (self.tokens + vergangen * limits.pro_sekunde).min(limits.burst as f64);
+        self.stand = jetzt;
+    }
+
+    fn ist_voll(&self, limits: &PingLimits) -> bool {
+        self.tokens >= limits.burst as f64
+    }
+}
+
+/// Beantwortet Pings mit Rate-Limit pro Quell-IP
+#[derive(Debug)]
+pub struct PingReflektor {
+    limits: PingLimits,
+    eimer: Mutex<HashMap<IpAddr, Eimer>>,
+    beantwortet: AtomicU64,
+    gedrosselt: AtomicU64,
+    ungueltig: AtomicU64,
+}
+
+impl PingReflektor {
+    /// Erstellt einen Reflektor mit den gegebenen Grenzen
+    pub fn neu(limits: PingLimits) -> Self {
+        Self {
+            limits,
+            eimer: Mutex::new(HashMap::new()),
+            beantwortet: AtomicU64::new(0),
+            gedrosselt: AtomicU64::new(0),
+            ungueltig: AtomicU64::new(0),
+        }
+    }
+
+    /// Prueft eine Anfrage und gibt die Antwort zurueck (`None` = verwerfen)
+    pub fn verarbeiten(&self, daten: &[u8], absender: IpAddr) -> Option<[u8; PING_GROESSE]> {
+        let server_zeit_ms = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or(0);
+        self.verarbeiten_um(daten, absender, Instant::now(), server_zeit_ms)
+    }
+
+    /// Wie [`Self::verarbeiten`], mit vorgegebener Uhr und Serverzeit
+    pub fn verarbeiten_um(
+        &self,
+        daten: &[u8],
+        absender: IpAddr,
+        jetzt: Instant,
+        server_zeit_ms: u64,
+    ) -> Option<[u8; PING_GROESSE]> {
+        let ping = match PingPaket::decode(daten) {
+            Ok(ping) if !ping.antwort => ping,
+            _ => {
+                self.ungueltig.fetch_add(1, Ordering::Relaxed);
+                return None;
+            }
+        };
+        if !self.erlauben(absender, jetzt) {
+            self.gedrosselt.fetch_add(1, Ordering::Relaxed);
+            return None;
+        }
+        self.beantwortet.fetch_add(1, Ordering::Relaxed);
+        Some(ping.beantworten(server_zeit_ms).encode())
+    }
+
+    /// Entnimmt ein Token aus dem Eimer der Quell-IP
+    fn erlauben(&self, absender: IpAddr, jetzt: Instant) -> bool {
+        let limits = &self.limits;
+        let mut eimer = self.eimer.lock();
+        if !eimer.contains_key(&absender) && eimer.len() >= limits.max_quellen {
+            // Volle Eimer entsprechen dem Anfangszustand und koennen weg
+            eimer.retain(|_, e| {
+                e.auffuellen(jetzt, limits);
+                !e.ist_voll(limits)
+            });
+            if eimer.len() >= limits.max_quellen {
+                return false;
+            }
+        }
+        let e = eimer.entry(absender).or_insert(Eimer {
+            tokens: limits.burst as f64,
+            stand: jetzt,
+        });
+        e.auffuellen(jetzt, limits);
+        if e.tokens >= 1.0 {
+            e.tokens -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Anzahl beantworteter Pings seit dem Start
+    pub fn beantwortet(&self) -> u64 {
+        self.beantwortet.load(Ordering::Relaxed)
+    }
+
+    /// Anzahl wegen des Rate-Limits verworfener Pings seit dem Start
+    pub fn gedrosselt(&self) -> u64 {
+        self.gedrosselt.load(Ordering::Relaxed)
+    }
+
+    /// Anzahl ungueltiger Pings (Laenge, Header, Antworten) seit dem Start
+    pub fn ungueltig(&self) -> u64 {
+        self.ungueltig.load(Ordering::Relaxed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::Ipv4Addr;
+    use std::time::Duration;
+
+    fn ip(letztes: u8) -> IpAddr {
+        IpAddr::V4(Ipv4Addr::new(192, 0, 2, letztes))
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn burst_dann_nachfuellen_pro_quelle() {
+        let reflektor = PingReflektor::neu(PingLimits {
+            pro_sekunde: 2.0,
+            burst: 3,
+            max_quellen: 16,
+        });
+        let anfrage = PingPaket::anfrage(1, 42).encode();
+        let jetzt = Instant::now();
+
+        for _ in 0..3 {
+            assert!(reflektor
+                .verarbeiten_um(&anfrage, ip(1), jetzt, 7)
+                .is_some());
+        }
+        assert!(reflektor
+            .verarbeiten_um(&anfrage, ip(1), jetzt, 7)
+            .is_none());
+        // Andere Quellen haben ihren eigenen Eimer
+        assert!(reflektor
+            .verarbeiten_um(&anfrage, ip(2), jetzt, 7)
+            .is_some());
+
+        // 2 pro Sekunde: nach 500 ms genau ein neues Token
+        let spaeter = jetzt + Duration::from_millis(500);
+        assert!(reflektor
+            .verarbeiten_um(&anfrage, ip(1), spaeter, 7)
+            .is_some());
+        assert!(reflektor
+            .verarbeiten_um(&anfrage, ip(1), spaeter, 7)
+            .is_none());
+
+        assert_eq!(reflektor.beantwortet(), 5);
+        assert_eq!(reflektor.gedrosselt(), 2);
+    }
+
+    #[test]
+    fn antwort_spiegelt_die_anfrage() {
+        let reflektor = PingReflektor::neu(PingLimits::default());
+        let antwort = reflektor
+            .verarbeiten_um(
+                &PingPaket::anfrage(9, 1234).encode(),
+                ip(1),
+                Instant::now(),
+                1_700_000_000_000,
+            )
+            .unwrap();
+        let antwort = PingPaket::decode(&antwort).unwrap();
+        assert!(antwort.antwort);
+        assert_eq!(antwort.sequenz, 9);
+        assert_eq!(antwort.client_zeit, 1234);
+        assert_eq!(antwort.server_zeit_ms, 1_700_000_000_000);
+    }
+
+    #[test]
+    fn falsche_groesse_und_antworten_werden_verworfen() {
+        let reflektor = PingReflektor::neu(PingLimits::default());
+        let jetzt = Instant::now();
+        let anfrage = PingPaket::anfrage(1, 0).encode();
+
+        let mut zu_lang = anfrage.to_vec();
+        zu_lang.extend_from_slice(&[0; 64]);
+        assert!(reflektor
+            .verarbeiten_um(&zu_lang, ip(1), jetzt, 0)
+            .is_none());
+        assert!(reflektor
+            .verarbeiten_um(&anfrage[..PING_GROESSE - 1], ip(1), jetzt, 0)
+            .is_none());
+        let antwort = PingPaket::anfrage(1, 0).beantworten(5).encode();
+        assert!(reflektor
+            .verarbeiten_um(&antwort, ip(1), jetzt, 0)
+            .is_none());
+
+        assert_eq!(reflektor.ungueltig(), 3);
+        assert_eq!(reflektor.beantwortet(), 0);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn anzahl_der_quellen_ist_begrenzt() {
+        let reflektor = PingReflektor::neu(PingLimits {
+            pro_sekunde: 1.0,
+            burst: 2,
+            max_quellen: 2,
+        });
+        let anfrage = PingPaket::anfrage(1, 0).encode();
+        let jetzt = Instant::now();
+
+        assert!(reflektor
+            .verarbeiten_um(&anfrage, ip(1), jetzt, 0)
+            .is_some());
+        assert!(reflektor
+            .verarbeiten_um(&anfrage, ip(2), jetzt, 0)
+            .is_some());
+        // Beide Eimer angebrochen: keine dritte Quelle
+        assert!(reflektor
+            .verarbeiten_um(&anfrage, ip(3), jetzt, 0)
+            .is_none());
+
+        // Wieder volle Eimer werden verdraengt
+        let spaeter = jetzt + Duration::from_secs(2);
+        assert!(reflektor
+            .verarbeiten_um(&anfrage, ip(3), spaeter, 0)
+            .is_some());
+    }
+}
@@ -11,12 +11,30 @@
 //! ## Multichannel-Unterstuetzung
 //! Ein Client kann genau einem Kanal gleichzeitig angehoeren.
 //! Der Router leitet an N-1 Teilnehmer weiter (alle ausser Absender).
-
+//!
+//! ## Notfall-Stummschaltung
+//! Ist der Kanal des Absenders per [`NotfallStumm`] stumm geschaltet und der
+//! Absender nicht ausgenommen, wird das Paket verworfen.
+//!
+//! ## Resequenzierung
+//! Absichtlich verworfene Pakete (Nur-Zuhoerer, Notfall-Stummschaltung,
+//! veraltete Pakete, volle Send-Queue) hinterlassen beim Empfaenger
+//! Sequenzluecken, die Jitter-Buffer und Verluststatistik als Netzwerkverlust
+//! werten. Je Strecke (Absender -> Empfaenger) zaehlt der Router diese Pakete
+//! und schreibt bei [`Resequenzierung::Umschreiben`] Sequenznummer (und nach
+//! langen Pausen den Zeitstempel) der ausgehenden Kopie fortlaufend um.
+//! Echter Verlust bleibt als Luecke sichtbar. Bei E2E darf der Header nicht
+//! veraendert werden; dort gilt [`Resequenzierung::Melden`] und der
+//! Empfaenger erfaehrt die Anzahl ueber die Voice-Statistik.
+
+use crate::notfall::NotfallStumm;
 use dashmap::DashMap;
 use speakeasy_core::types::{ChannelId, UserId};
-use speakeasy_protocol::voice::VoicePacket;
+use speakeasy_protocol::voice::{VoicePacket, VoicePacketHeader};
 use std::net::SocketAddr;
+use std::sync::atomic::{AtomicU32, Ordering};
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::mpsc;
 
 // ---------------------------------------------------------------------------
@@ -26,6 +44,99 @@ use tokio::sync::mpsc;
 /// Groesse der Send-Queue pro Client (Pakete)
 pub const SEND_QUEUE_GROESSE: usize = 128;
 
+/// RTP-Takt der Voice-Zeitstempel (Ticks pro Millisekunde)
+const TICKS_PRO_MS: u64 = 48;
+
+// ---------------------------------------------------------------------------
+// Resequenzierung
+// ---------------------------------------------------------------------------
+
+/// Umgang mit absichtlich verworfenen Paketen fuer einen Empfaenger
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Resequenzierung {
+    /// Pakete unveraendert weiterleiten, nichts zaehlen
+    #[default]
+    Aus,
+    /// Sequenznummer und ggf. Zeitstempel je Strecke fortlaufend umschreiben
+    Umschreiben,
+    /// Header unveraendert lassen (E2E), verworfene Pakete aber zaehlen
+    Melden,
+}
+
+/// Sequenznummer und Zeitstempel einer ausgehenden Kopie
+type HeaderFelder = (u32, u32);
+
+/// Zustand einer Strecke (Absender -> Empfaenger)
+#[derive(Debug, Default)]
+struct Strecke {
+    /// SSRC des Absenders – bei einem Wechsel beginnt die Strecke neu
+    ssrc: u32,
+    /// Bisher verworfene Pakete (wird von der Sequenznummer abgezogen)
+    sequenz_versatz: u32,
+    /// Uebersprungene Ticks langer Pausen (wird vom Zeitstempel abgezogen)
+    zeitstempel_versatz: u32,
+    /// Zeitstempel des zuletzt weitergeleiteten Pakets
+    letzter_zeitstempel: Option<u32>,
+    /// Zeitstempel des letzten verworfenen Pakets seit der letzten Weiterleitung
+    pause_bis: Option<u32>,
+    /// Verworfene Pakete dieser SSRC (kumuliert)
+    verworfen: u64,
+}
+
+impl Strecke {
+    fn ssrc_pruefen(&mut self, ssrc: u32) {
+        if self.ssrc != ssrc {
+            *self = Self {
+                ssrc,
+                ..Self::default()
+            };
+        }
+    }
+
+    /// Verbucht ein absichtlich nicht zugestelltes Paket
+    fn verwerfen(&mut self, header: &VoicePacketHeader) {
+        self.ssrc_pruefen(header.ssrc);
+        self.sequenz_versatz = self.sequenz_versatz.wrapping_add(1);
+        self.pause_bis = Some(header.timestamp);
+        self.verworfen += 1;
+    }
+
+    /// Sequenznummer und Zeitstempel fuer die ausgehende Kopie
+    ///
+    /// Dauerte die Pause seit dem letzten zugestellten Paket mindestens
+    /// `zeitstempel_schwelle` Ticks (0 = nie), wird sie aus dem Zeitstempel
+    /// herausgenommen.
+    fn weiterleiten(
+        &mut self,
+        header: &VoicePacketHeader,
+        zeitstempel_schwelle: u32,
+    ) -> HeaderFelder {
+        self.ssrc_pruefen(header.ssrc);
+        if let (Some(bis), Some(letzter)) = (self.pause_bis.take(), self.letzter_zeitstempel) {
+            let pause = bis.wrapping_sub(letzter);
+            // Negative Spannen (umsortierte Pakete) ignorieren
+            if zeitstempel_schwelle > 0 && pause >= zeitstempel_schwelle && pause < u32::MAX / 2 {
+                self.zeitstempel_versatz = self.zeitstempel_versatz.wrapping_add(pause);
+            }
+        }
+        self.letzter_zeitstempel = Some(header.timestamp);
+        (
+            header.sequence.wrapping_sub(self.sequenz_versatz),
+            header.timestamp.wrapping_sub(self.zeitstempel_versatz),
+        )
+    }
+}
+
+/// Kopie eines kodierten Pakets mit umgeschriebenem Header
+fn umgeschrieben(paket_bytes: &[u8], (sequenz, zeitstempel): HeaderFelder) -> Arc<Vec<u8>> {
+    let mut kopie = paket_bytes.to_vec();
+    VoicePacketHeader::sequenz_schreiben(&mut kopie, sequenz)
+        .expect("kodiertes Paket enthaelt einen vollstaendigen Header");
+    VoicePacketHeader::zeitstempel_schreiben(&mut kopie, zeitstempel)
+        .expect("kodiertes Paket enthaelt einen vollstaendigen Header");
+    Arc::new(kopie)
+}
+
 // ---------------------------------------------------------------------------
 // Teilnehmer-Info
 // ---------------------------------------------------------------------------
@@ -51,6 +162,8 @@ struct VoiceChannel {
     kanal_id: ChannelId,
     /// Teilnehmer, indexiert nach UserId
     teilnehmer: DashMap<UserId, Teilnehmer>,
+    /// Strecken mit Resequenzierung, indexiert nach (Absender, Empfaenger)
+    strecken: DashMap<(UserId, UserId), Strecke>,
 }
 
 impl VoiceChannel {
@@ -58,6 +171,7 @@ impl VoiceChannel {
         Self {
             kanal_id,
             teilnehmer: DashMap::new(),
+            strecken: DashMap::new(),
         }
     }
 
@@ -81,23 +195,76 @@ impl VoiceChannel {
 
     /// Entfernt einen Teilnehmer
     fn teilnehmer_entfernen(&self, user_id: &UserId) -> bool {
+        self.strecken
+            .retain(|(absender, empfaenger), _| absender != user_id && empfaenger != user_id);
         self.teilnehmer.remove(user_id).is_some()
     }
 
+    /// Verbucht ein verworfenes Paket auf allen Strecken des Absenders
+    fn paket_verworfen(
+        &self,
+        header: &VoicePacketHeader,
+        absender: &UserId,
+        modi: &DashMap<UserId, Resequenzierung>,
+    ) {
+        self.teilnehmer.iter().for_each(|entry| {
+            if &entry.user_id != absender && modi.contains_key(&entry.user_id) {
+                self.strecken
+                    .entry((*absender, entry.user_id))
+                    .or_default()
+                    .verwerfen(header);
+            }
+        });
+    }
+
     /// Leitet ein Paket an alle Teilnehmer ausser dem Absender weiter
     ///
     /// Erstellt eine Arc<Vec<u8>> einmal und klont nur den Arc (kein Memcpy).
+    /// Nur Strecken, deren Header umgeschrieben werden muss, erhalten eine
+    /// Kopie; Strecken mit gleichem Versatz teilen sie sich.
     /// Gibt die Anzahl der erfolgreichen Weiterleitungen zurueck.
-    fn paket_weiterleiten(&self, paket_bytes: Arc<Vec<u8>>, absender: &UserId) -> usize {
+    fn paket_weiterleiten(
+        &self,
+        header: &VoicePacketHeader,
+        paket_bytes: Arc<Vec<u8>>,
+        absender: &UserId,
+        router: &ChannelRouterInner,
+    ) -> usize {
         let mut weitergeleitet = 0usize;
+        let zeitstempel_schwelle = router.zeitstempel_schwelle.load(Ordering::Relaxed);
+        let mut kopie: Option<(HeaderFelder, Arc<Vec<u8>>)> = None;
 
         self.teilnehmer.iter().for_each(|entry| {
             if &entry.user_id == absender {
                 return; // Nicht an Absender zurueckschicken
             }
 
+            let modus = router
+                .resequenzierung
+                .get(&entry.user_id)
+                .map(|m| *m)
+                .unwrap_or_default();
+            let bytes = if modus == Resequenzierung::Umschreiben {
+                let felder = self
+                    .strecken
+                    .entry((*absender, entry.user_id))
+                    .or_default()
+                    .weiterleiten(header, zeitstempel_schwelle);
+                match &kopie {
+                    _ if felder == (header.sequence, header.timestamp) => Arc::clone(&paket_bytes),
+                    Some((k, bytes)) if *k == felder => Arc::clone(bytes),
+                    _ => {
+                        let bytes = umgeschrieben(&paket_bytes, felder);
+                        kopie = Some((felder, Arc::clone(&bytes)));
+                        bytes
+                    }
+                }
+            } else {
+                Arc::clone(&paket_bytes)
+            };
+
             // Nicht-blockierend senden – bei voller Queue verwerfen (UDP-Semantik)
-            match entry.send_tx.try_send(Arc::clone(&paket_bytes)) {
+            match entry.send_tx.try_send(bytes) {
                 Ok(()) => weitergeleitet += 1,
                 Err(mpsc::error::TrySendError::Full(_)) => {
                     tracing::warn!(
@@ -105,6 +272,12 @@ impl VoiceChannel {
                         kanal = %self.kanal_id,
                         "Send-Queue voll – Paket verworfen"
                     );
+                    if modus != Resequenzierung::Aus {
+                        self.strecken
+                            .entry((*absender, entry.user_id))
+                            .or_default()
+                            .verwerfen(header);
+                    }
                 }
                 Err(mpsc::error::TrySendError::Closed(_)) => {
                     tracing::debug!(
@@ -141,19 +314,103 @@ struct ChannelRouterInner {
     kanaele: DashMap<ChannelId, VoiceChannel>,
     /// Client -> Kanal Mapping fuer schnelles Leave
     client_kanal: DashMap<UserId, ChannelId>,
+    /// Notfall-stumm geschaltete Kanaele (ggf. mit anderen Routern geteilt)
+    notfall: NotfallStumm,
+    /// Ausgehandelte Resequenzierung je Empfaenger (fehlend = aus)
+    resequenzierung: DashMap<UserId, Resequenzierung>,
+    /// Pausen ab dieser Laenge (Ticks) entfallen im Zeitstempel, 0 = nie
+    zeitstempel_schwelle: AtomicU32,
 }
 
 impl ChannelRouter {
     /// Erstellt einen neuen leeren Channel Router
     pub fn neu() -> Self {
+        Self::mit_notfall(NotfallStumm::neu())
+    }
+
+    /// Erstellt einen leeren Router mit geteilter Notfall-Stummschaltung
+    pub fn mit_notfall(notfall: NotfallStumm) -> Self {
         Self {
             inner: Arc::new(ChannelRouterInner {
                 kanaele: DashMap::new(),
                 client_kanal: DashMap::new(),
+                notfall,
+                resequenzierung: DashMap::new(),
+                zeitstempel_schwelle: AtomicU32::new(0),
             }),
         }
     }
 
+    /// Gibt die Notfall-Stummschaltung des Routers zurueck
+    pub fn notfall(&self) -> &NotfallStumm {
+        &self.inner.notfall
+    }
+
+    /// Legt die ausgehandelte Resequenzierung fuer einen Empfaenger fest
+    pub fn resequenzierung_setzen(&self, empfaenger: UserId, modus: Resequenzierung) {
+        if modus == Resequenzierung::Aus {
+            self.inner.resequenzierung.remove(&empfaenger);
+        } else {
+            self.inner.resequenzierung.insert(empfaenger, modus);
+        }
+    }
+
+    /// Nimmt Pausen ab `dauer` beim Umschreiben auch aus dem Zeitstempel
+    /// heraus (`None` = Zeitstempel nie umschreiben)
+    ///
+    /// Kurze Pausen bleiben erhalten, damit der Empfaenger sie wie DTX
+    /// ausspielt; nach langen Pausen setzt der Strom nahtlos fort.
+    pub fn zeitstempel_glaetten_ab(&self, dauer: Option<Duration>) {
+        let ticks = dauer.map_or(0, |d| {
+            (d.as_millis() as u64 * TICKS_PRO_MS).clamp(1, u64::from(u32::MAX / 2)) as u32
+        });
+        self.inner
+            .zeitstempel_schwelle
+            .store(ticks, Ordering::Relaxed);
+    }
+
+    /// Verbucht ein absichtlich nicht weitergeleitetes Paket
+    ///
+    /// Fuer alle Empfaenger mit Resequenzierung im Kanal des Absenders; ohne
+    /// diesen Aufruf erscheint das Paket beim Empfaenger als Verlust.
+    pub fn paket_unterdrueckt(&self, header: &VoicePacketHeader, absender: &UserId) {
+        if self.inner.resequenzierung.is_empty() {
+            return;
+        }
+        let Some(kanal_id) = self.kanal_von_client(absender) else {
+            return;
+        };
+        if let Some(kanal) = self.inner.kanaele.get(&kanal_id) {
+            kanal.paket_verworfen(header, absender, &self.inner.resequenzierung);
+        }
+    }
+
+    /// Fuer einen Empfaenger verworfene Pakete je SSRC (kumuliert)
+    pub fn unterdrueckt_fuer(&self, empfaenger: &UserId) -> Vec<(u32, u64)> {
+        let Some(kanal_id) = self.kanal_von_client(empfaenger) else {
+            return Vec::new();
+        };
+        let Some(kanal) = self.inner.kanaele.get(&kanal_id) else {
+            return Vec::new();
+        };
+        let mut liste: Vec<(u32, u64)> = kanal
+            .strecken
+            .iter()
+            .filter(|s| &s.key().1 == empfaenger && s.verworfen > 0)
+            .map(|s| (s.ssrc, s.verworfen))
+            .collect();
+        liste.sort_unstable();
+        liste
+    }
+
+    /// Prueft ob Pakete eines Clients wegen Notfall-Stummschaltung verworfen werden
+    pub fn notfall_unterdrueckt(&self, absender: &UserId) -> bool {
+        self.inner
+            .client_kanal
+            .get(absender)
+            .is_some_and(|kanal_id| self.inner.notfall.unterdrueckt(&kanal_id, absender))
+    }
+
     /// Ein Client tritt einem Kanal bei
     ///
     /// Falls der Client bereits in einem anderen Kanal ist, wird er zuerst
@@ -211,7 +468,8 @@ impl ChannelRouter {
     /// Das Paket wird einmal serialisiert und als `Arc<Vec<u8>>` ohne Kopie
     /// an alle Empfaenger-Queues gesendet.
     ///
-    /// Gibt die Anzahl der erfolgreichen Weiterleitungen zurueck (0 bei Fehler).
+    /// Gibt die Anzahl der erfolgreichen Weiterleitungen zurueck (0 bei Fehler
+    /// oder Notfall-Stummschaltung).
     pub fn paket_weiterleiten(&self, paket: &VoicePacket, absender: &UserId) -> usize {
         // Kanal des Absenders ermitteln
         let kanal_id = match self.inner.client_kanal.get(absender) {
@@ -230,9 +488,19 @@ impl ChannelRouter {
             }
         };
 
+        if self.inner.notfall.unterdrueckt(&kanal_id, absender) {
+            tracing::trace!(
+                absender = %absender,
+                kanal_id = %kanal_id,
+                "Paket verworfen (Notfall-Stummschaltung)"
+            );
+            kanal.paket_verworfen(&paket.header, absender, &self.inner.resequenzierung);
+            return 0;
+        }
+
         // Paket einmal serialisieren, dann als Arc weiterreichen (zero-copy)
         let paket_bytes = Arc::new(paket.encode());
-        let count = kanal.paket_weiterleiten(paket_bytes, absender);
+        let count = kanal.paket_weiterleiten(&paket.header, paket_bytes, absender, &self.inner);
 
         tracing::trace!(
             absender = %absender,
@@ -244,6 +512,29 @@ impl ChannelRouter {
         count
     }
 
+    /// Speist ein serverseitig erzeugtes Paket in einen Kanal ein
+    ///
+    /// Wie [`paket_weiterleiten`](Self::paket_weiterleiten), nur dass der
+    /// Absender (etwa eine Soundboard-Wiedergabe) kein Teilnehmer ist und
+    /// das Paket daher alle Teilnehmer erreicht. Die Notfall-Stummschaltung
+    /// gilt auch hier.
+    pub fn paket_einspeisen(
+        &self,
+        kanal_id: &ChannelId,
+        paket: &VoicePacket,
+        absender: &UserId,
+    ) -> usize {
+        let Some(kanal) = self.inner.kanaele.get(kanal_id) else {
+            return 0;
+        };
+        if self.inner.notfall.unterdrueckt(kanal_id, absender) {
+            kanal.paket_verworfen(&paket.header, absender, &self.inner.resequenzierung);
+            return 0;
+        }
+        let paket_bytes = Arc::new(paket.encode());
+        kanal.paket_weiterleiten(&paket.header, paket_bytes, absender, &self.inner)
+    }
+
     /// Gibt die Anzahl der Teilnehmer in einem Kanal zurueck
     pub fn teilnehmer_anzahl(&self, kanal_id: &ChannelId) -> usize {
         self.inner
@@ -306,7 +597,7 @@ impl Default for ChannelRouter {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use speakeasy_protocol::voice::VoicePacket;
+    use speakeasy_protocol::voice::{SequenzStatistik, VoicePacket};
     use std::net::{IpAddr, Ipv4Addr};
 
     fn endpunkt(port: u16) -> SocketAddr {
@@ -433,6 +724,176 @@ mod tests {
         assert!(rx_a1.try_recv().is_err(), "user_a1 kein Echo");
     }
 
+    #[tokio::test]
+    async fn notfall_stummschaltung_unterdrueckt_sofort() {
+        let notfall = NotfallStumm::neu();
+        let router = ChannelRouter::mit_notfall(notfall.clone());
+        let kanal = ChannelId::new();
+        let moderator = UserId::new();
+        let gast = UserId::new();
+
+        let mut rx_moderator = router.kanal_beitreten(moderator, kanal, endpunkt(20050));
+        let mut rx_gast = router.kanal_beitreten(gast, kanal, endpunkt(20051));
+
+        assert_eq!(router.paket_weiterleiten(&test_paket(1, 0x2222), &gast), 1);
+        assert!(rx_moderator.try_recv().is_ok());
+
+        // Direkt das naechste Paket wird verworfen, der Moderator bleibt hoerbar
+        notfall.aktivieren(kanal, [moderator]);
+        assert!(router.notfall_unterdrueckt(&gast));
+        assert_eq!(router.paket_weiterleiten(&test_paket(2, 0x2222), &gast), 0);
+        assert!(rx_moderator.try_recv().is_err());
+        assert_eq!(
+            router.paket_weiterleiten(&test_paket(1, 0x1111), &moderator),
+            1
+        );
+        assert!(rx_gast.try_recv().is_ok());
+
+        notfall.deaktivieren(&kanal);
+        assert!(!router.notfall_unterdrueckt(&gast));
+        assert_eq!(router.paket_weiterleiten(&test_paket(3, 0x2222), &gast), 1);
+        let bytes = rx_moderator.try_recv().expect("Routing wieder aktiv");
+        assert_eq!(VoicePacket::decode(&bytes).unwrap().header.sequence, 3);
+    }
+
+    #[tokio::test]
+    async fn notfall_gilt_auch_fuer_spaetere_beitritte() {
+        let router = ChannelRouter::neu();
+        let kanal = ChannelId::new();
+        let anwesend = UserId::new();
+        let neu = UserId::new();
+        let mut rx = router.kanal_beitreten(anwesend, kanal, endpunkt(20060));
+
+        router.notfall().aktivieren(kanal, []);
+        let _rx_neu = router.kanal_beitreten(neu, kanal, endpunkt(20061));
+        assert_eq!(router.paket_weiterleiten(&test_paket(1, 0x3333), &neu), 0);
+        assert!(rx.try_recv().is_err());
+    }
+
+    /// Alle bisher beim Empfaenger angekommenen Header
+    fn empfangen(rx: &mut mpsc::Receiver<Arc<Vec<u8>>>) -> Vec<VoicePacketHeader> {
+        let mut header = Vec::new();
+        while let Ok(bytes) = rx.try_recv() {
+            header.push(VoicePacket::decode(&bytes).unwrap().header);
+        }
+        header
+    }
+
+    fn luecken(header: &[VoicePacketHeader]) -> u64 {
+        let mut statistik = SequenzStatistik::default();
+        header.iter().for_each(|h| statistik.paket(h.sequence));
+        statistik.verloren()
+    }
+
+    #[tokio::test]
+    async fn resequenzierung_verbirgt_nur_absichtlich_verworfene_pakete() {
+        let router = ChannelRouter::neu();
+        let kanal = ChannelId::new();
+        let sprecher = UserId::new();
+        let umschreibend = UserId::new();
+        let unveraendert = UserId::new();
+        let _rx = router.kanal_beitreten(sprecher, kanal, endpunkt(20070));
+        let mut rx_um = router.kanal_beitreten(umschreibend, kanal, endpunkt(20071));
+        let mut rx_alt = router.kanal_beitreten(unveraendert, kanal, endpunkt(20072));
+        router.resequenzierung_setzen(umschreibend, Resequenzierung::Umschreiben);
+
+        for seq in 1..=3 {
+            router.paket_weiterleiten(&test_paket(seq, 0x4444), &sprecher);
+        }
+        // Notfall-Stummschaltung im Router und ein verworfenes Paket im UDP-Pfad
+        router.notfall().aktivieren(kanal, []);
+        for seq in 4..=7 {
+            assert_eq!(
+                router.paket_weiterleiten(&test_paket(seq, 0x4444), &sprecher),
+                0
+            );
+        }
+        router.notfall().deaktivieren(&kanal);
+        router.paket_unterdrueckt(&test_paket(8, 0x4444).header, &sprecher);
+        for seq in 9..=10 {
+            router.paket_weiterleiten(&test_paket(seq, 0x4444), &sprecher);
+        }
+        // Paket 11 geht auf dem Weg zum Server verloren
+        router.paket_weiterleiten(&test_paket(12, 0x4444), &sprecher);
+
+        let umgeschrieben = empfangen(&mut rx_um);
+        let sequenzen: Vec<u32> = umgeschrieben.iter().map(|h| h.sequence).collect();
+        assert_eq!(sequenzen, vec![1, 2, 3, 4, 5, 7]);
+        assert_eq!(
+            luecken(&umgeschrieben),
+            1,
+            "Netzwerkverlust bleibt sichtbar"
+        );
+        assert_eq!(luecken(&empfangen(&mut rx_alt)), 6);
+
+        // Ohne Luecke weitergeleitete Pakete behalten ihre Zeitstempel
+        assert_eq!(umgeschrieben[3].timestamp, 9 * 960);
+    }
+
+    #[tokio::test]
+    async fn zeitstempel_nur_nach_langen_pausen_fortlaufend() {
+        let router = ChannelRouter::neu();
+        let kanal = ChannelId::new();
+        let sprecher = UserId::new();
+        let hoerer = UserId::new();
+        let _rx = router.kanal_beitreten(sprecher, kanal, endpunkt(20080));
+        let mut rx = router.kanal_beitreten(hoerer, kanal, endpunkt(20081));
+        router.resequenzierung_setzen(hoerer, Resequenzierung::Umschreiben);
+        router.zeitstempel_glaetten_ab(Some(Duration::from_millis(100)));
+
+        let mut seq = 1;
+        let mut senden = |anzahl: u32, zustellen: bool| {
+            for _ in 0..anzahl {
+                let paket = test_paket(seq, 0x5555);
+                if zustellen {
+                    router.paket_weiterleiten(&paket, &sprecher);
+                } else {
+                    router.paket_unterdrueckt(&paket.header, &sprecher);
+                }
+                seq += 1;
+            }
+        };
+        senden(2, true);
+        // Kurze Pause (40 ms): Zeitstempel behalten ihren Abstand
+        senden(2, false);
+        senden(1, true);
+        // Lange Pause (200 ms): der Strom setzt nahtlos fort
+        senden(10, false);
+        senden(2, true);
+
+        let header = empfangen(&mut rx);
+        let zeitstempel: Vec<u32> = header.iter().map(|h| h.timestamp).collect();
+        assert_eq!(zeitstempel, vec![960, 1920, 4800, 5760, 6720]);
+        assert_eq!(luecken(&header), 0);
+    }
+
+    #[tokio::test]
+    async fn melden_laesst_header_unveraendert() {
+        let router = ChannelRouter::neu();
+        let kanal = ChannelId::new();
+        let sprecher = UserId::new();
+        let e2e = UserId::new();
+        let _rx = router.kanal_beitreten(sprecher, kanal, endpunkt(20090));
+        let mut rx = router.kanal_beitreten(e2e, kanal, endpunkt(20091));
+        router.resequenzierung_setzen(e2e, Resequenzierung::Melden);
+
+        router.paket_weiterleiten(&test_paket(1, 0x6666), &sprecher);
+        router.paket_unterdrueckt(&test_paket(2, 0x6666).header, &sprecher);
+        router.paket_unterdrueckt(&test_paket(3, 0x6666).header, &sprecher);
+        let paket = test_paket(4, 0x6666);
+        router.paket_weiterleiten(&paket, &sprecher);
+
+        let _erstes = rx.try_recv().unwrap();
+        assert_eq!(rx.try_recv().unwrap().as_ref(), &paket.encode());
+        assert_eq!(router.unterdrueckt_fuer(&e2e), vec![(0x6666, 2)]);
+        assert!(router.unterdrueckt_fuer(&sprecher).is_empty());
+
+        // Beim Verlassen beginnt die Zaehlung neu
+        router.kanal_verlassen(&e2e);
+        let _rx = router.kanal_beitreten(e2e, kanal, endpunkt(20091));
+        assert!(router.unterdrueckt_fuer(&e2e).is_empty());
+    }
+
     #[test]
     fn router_clone_teilt_state() {
         let router1 = ChannelRouter::neu();
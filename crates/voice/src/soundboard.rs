@@ -0,0 +1,237 @@
+//! Soundboard – serverseitiges Einspeisen kurzer Clips in einen Kanal
+//!
+//! Ein Clip liegt bereits als Folge von Opus-Frames vor. Jede Wiedergabe
+//! laeuft als eigener Task, der die Frames im Takt der Frame-Dauer ueber
+//! [`ChannelRouter::paket_einspeisen`] an alle Kanal-Teilnehmer verteilt.
+//! Fuer die Empfaenger sieht eine Wiedergabe wie ein weiterer Sprecher mit
+//! eigener SSRC aus: Sequenznummern beginnen bei 0, der Zeitstempel steigt
+//! um die Frame-Dauer in 48-kHz-Ticks.
+//!
+//! Die Wiedergabe-ID dient zugleich als Absender im Router. Sie ist nie ein
+//! Kanal-Teilnehmer, das Paket erreicht also alle Teilnehmer.
+
+use crate::router::ChannelRouter;
+use dashmap::DashMap;
+use speakeasy_core::types::{ChannelId, UserId};
+use speakeasy_protocol::voice::{VoiceFlags, VoicePacket};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::oneshot;
+use tokio::task::AbortHandle;
+
+/// RTP-Takt der Voice-Zeitstempel (Ticks pro Millisekunde)
+const TICKS_PRO_MS: u64 = 48;
+
+/// Eine laufende Wiedergabe
+struct Wiedergabe {
+    kanal_id: ChannelId,
+    ssrc: u32,
+    task: AbortHandle,
+}
+
+/// Laufende Soundboard-Wiedergaben aller Kanaele
+///
+/// Thread-safe und `Clone`-faehig (innerer Arc).
+#[derive(Clone)]
+pub struct Soundboard {
+    router: ChannelRouter,
+    wiedergaben: Arc<DashMap<UserId, Wiedergabe>>,
+}
+
+impl Soundboard {
+    /// Erstellt ein Soundboard, das ueber `router` einspeist
+    pub fn neu(router: ChannelRouter) -> Self {
+        Self {
+            router,
+            wiedergaben: Arc::new(DashMap::new()),
+        }
+    }
+
+    /// Startet die Wiedergabe eines Clips
+    ///
+    /// `id` muss eindeutig sein und kennzeichnet die Wiedergabe als Absender.
+    /// Der zurueckgegebene Empfaenger wird fertig, sobald die Wiedergabe
+    /// endet – regulaer nach dem letzten Frame oder durch Stoppen.
+    ///
+    /// Muss innerhalb einer Tokio-Runtime aufgerufen werden.
+    pub fn abspielen(
+        &self,
+        id: UserId,
+        kanal_id: ChannelId,
+        ssrc: u32,
+        frames: Arc<Vec<Vec<u8>>>,
+        frame_dauer: Duration,
+    ) -> oneshot::Receiver<()> {
+        let (fertig_tx, fertig_rx) = oneshot::channel();
+        let router = self.router.clone();
+        let wiedergaben = Arc::clone(&self.wiedergaben);
+        let ticks = (frame_dauer.as_millis() as u64 * TICKS_PRO_MS) as u32;
+
+        // Der Eintrag muss vor dem ersten Poll des Tasks stehen, sonst
+        // koennte ein sehr kurzer Clip ihn nicht mehr austragen
+        let eintrag = self.wiedergaben.entry(id);
+        let task = tokio::spawn(async move {
+            let mut takt = tokio::time::interval(frame_dauer);
+            let letzter = frames.len().saturating_sub(1);
+            for (i, frame) in frames.iter().enumerate() {
+                takt.tick().await;
+                let sequenz = i as u32;
+                let mut paket = VoicePacket::neu_audio(
+                    sequenz,
+                    sequenz.wrapping_mul(ticks),
+                    ssrc,
+                    frame.clone(),
+                );
+                if i == 0 {
+                    paket.header.flags |= VoiceFlags::SPEAKING_START;
+                }
+                if i == letzter {
+                    paket.header.flags |= VoiceFlags::SPEAKING_STOP;
+                }
+                router.paket_einspeisen(&kanal_id, &paket, &id);
+            }
+            wiedergaben.remove(&id);
+            let _ = fertig_tx.send(());
+        });
+        eintrag.insert(Wiedergabe {
+            kanal_id,
+            ssrc,
+            task: task.abort_handle(),
+        });
+
+        tracing::debug!(
+            wiedergabe = %id,
+            kanal_id = %kanal_id,
+            ssrc,
+            "Soundboard-Wiedergabe gestartet"
+        );
+        fertig_rx
+    }
+
+    /// Bricht eine Wiedergabe ab; `false`, wenn sie nicht (mehr) laeuft
+    pub fn stoppen(&self, id: &UserId) -> bool {
+        match self.wiedergaben.remove(id) {
+            Some((_, wiedergabe)) => {
+                wiedergabe.task.abort();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Bricht alle Wiedergaben in einem Kanal ab und gibt ihre IDs zurueck
+    pub fn kanal_stoppen(&self, kanal_id: &ChannelId) -> Vec<UserId> {
+        let ids = self.wiedergaben_in(kanal_id);
+        ids.iter().filter(|id| self.stoppen(id)).copied().collect()
+    }
+
+    /// Laufende Wiedergaben in einem Kanal
+    pub fn wiedergaben_in(&self, kanal_id: &ChannelId) -> Vec<UserId> {
+        self.wiedergaben
+            .iter()
+            .filter(|w| &w.kanal_id == kanal_id)
+            .map(|w| *w.key())
+            .collect()
+    }
+
+    /// Kanal einer laufenden Wiedergabe
+    pub fn kanal_von(&self, id: &UserId) -> Option<ChannelId> {
+        self.wiedergaben.get(id).map(|w| w.kanal_id)
+    }
+
+    /// SSRCs aller laufenden Wiedergaben
+    pub fn belegte_ssrcs(&self) -> Vec<u32> {
+        self.wiedergaben.iter().map(|w| w.ssrc).collect()
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::SocketAddr;
+    use tokio::time::Instant;
+
+    fn endpunkt(port: u16) -> SocketAddr {
+        SocketAddr::from(([127, 0, 0, 1], port))
+    }
+
+    fn frames(anzahl: u8) -> Arc<Vec<Vec<u8>>> {
+        Arc::new((0..anzahl).map(|i| vec![i; 40]).collect())
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn frames_werden_im_takt_eingespeist() {
+        let router = ChannelRouter::neu();
+        let soundboard = Soundboard::neu(router.clone());
+        let kanal = ChannelId::new();
+        let mut rx = router.kanal_beitreten(UserId::new(), kanal, endpunkt(21000));
+
+        let id = UserId::new();
+        let start = Instant::now();
+        let fertig = soundboard.abspielen(id, kanal, 0x5B00, frames(5), Duration::from_millis(20));
+        assert_eq!(soundboard.wiedergaben_in(&kanal), [id]);
+
+        let mut ankunft = Vec::new();
+        for erwartet in 0..5u32 {
+            let bytes = rx.recv().await.unwrap();
+            ankunft.push(start.elapsed());
+            let paket = VoicePacket::decode(&bytes).unwrap();
+            assert_eq!(paket.header.ssrc, 0x5B00);
+            assert_eq!(paket.header.sequence, erwartet);
+            assert_eq!(paket.header.timestamp, erwartet * 960);
+            assert_eq!(paket.payload, vec![erwartet as u8; 40]);
+            assert_eq!(paket.spricht_start(), erwartet == 0);
+            assert_eq!(paket.spricht_stop(), erwartet == 4);
+        }
+        // Erster Frame sofort, danach alle 20 ms
+        let erwartet: Vec<Duration> = (0..5).map(|i| Duration::from_millis(20 * i)).collect();
+        assert_eq!(ankunft, erwartet);
+
+        fertig.await.unwrap();
+        assert!(soundboard.wiedergaben_in(&kanal).is_empty());
+        assert!(soundboard.belegte_ssrcs().is_empty());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn stoppen_bricht_ab() {
+        let router = ChannelRouter::neu();
+        let soundboard = Soundboard::neu(router.clone());
+        let kanal = ChannelId::new();
+        let mut rx = router.kanal_beitreten(UserId::new(), kanal, endpunkt(21001));
+
+        let id = UserId::new();
+        let fertig = soundboard.abspielen(id, kanal, 7, frames(50), Duration::from_millis(20));
+        rx.recv().await.unwrap();
+        rx.recv().await.unwrap();
+
+        assert_eq!(soundboard.kanal_stoppen(&kanal), [id]);
+        assert!(!soundboard.stoppen(&id));
+        // Abbruch beendet die Wiedergabe ohne regulaeres Ende
+        assert!(fertig.await.is_err());
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        assert!(rx.try_recv().is_err());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn notfall_stummschaltung_gilt_fuer_clips() {
+        let router = ChannelRouter::neu();
+        let soundboard = Soundboard::neu(router.clone());
+        let kanal = ChannelId::new();
+        let mut rx = router.kanal_beitreten(UserId::new(), kanal, endpunkt(21002));
+        router.notfall().aktivieren(kanal, []);
+
+        let fertig = soundboard.abspielen(
+            UserId::new(),
+            kanal,
+            9,
+            frames(3),
+            Duration::from_millis(20),
+        );
+        fertig.await.unwrap();
+        assert!(rx.try_recv().is_err());
+    }
+}
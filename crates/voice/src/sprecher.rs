@@ -0,0 +1,440 @@
+//! Sprecher-Tracker – wer spricht gerade in welchem Kanal
+//!
+//! Der Sprechzustand stammt aus den Flags der Sprachpakete
+//! (`SPEAKING_START`/`SPEAKING_STOP`) und der Paketaktivitaet: Jedes
+//! Audiopaket verlaengert die Sprechphase, ohne Pakete endet sie nach der
+//! Nachlaufzeit ([`SPRECH_NACHLAUF`]), auch wenn das Stop-Flag verloren ging.
+//!
+//! Aufgeteilt in zwei Seiten:
+//! - [`SprecherTracker`] wird aus dem UDP-Empfangspfad gespeist. Der
+//!   Voice-State loest den Eintrag einmal pro Sitzung als [`SprecherSitzung`]
+//!   auf, pro Paket fallen nur atomare Zugriffe an; nur ein Zustandswechsel
+//!   landet in der Aenderungs-Warteschlange.
+//! - [`SprecherDrossel`] leert die Warteschlange in einem eigenen Task mit
+//!   niedriger Frequenz und meldet hoechstens einen Wechsel pro Benutzer und
+//!   [`MELDE_ABSTAND`]. Schnelles Flattern (Start/Stop/Start) wird dabei zum
+//!   letzten Zustand zusammengefasst.
+//!
+//! Verwendet `tokio::time::Instant`, damit Tests die Uhr per
+//! `tokio::time::pause()`/`advance()` steuern koennen.
+
+use dashmap::DashMap;
+use parking_lot::Mutex;
+use speakeasy_core::types::UserId;
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio::time::Instant;
+
+/// Nachlaufzeit: so lange gilt ein Client nach dem letzten Paket als sprechend
+pub const SPRECH_NACHLAUF: Duration = Duration::from_millis(500);
+/// Mindestabstand zweier gemeldeter Wechsel desselben Benutzers
+pub const MELDE_ABSTAND: Duration = Duration::from_millis(300);
+/// Intervall, in dem die Drossel die Warteschlange leert
+pub const ABFRAGE_INTERVALL: Duration = Duration::from_millis(100);
+
+/// Sprechzustand eines Benutzers (nur atomare Felder)
+struct SprecherEintrag {
+    spricht: AtomicBool,
+    /// Letztes Paket in ms seit `basis` des Trackers
+    letztes_paket_ms: AtomicU64,
+}
+
+struct TrackerInner {
+    eintraege: DashMap<UserId, Arc<SprecherEintrag>>,
+    nachlauf: Duration,
+    basis: Instant,
+    aenderungen_tx: mpsc::UnboundedSender<UserId>,
+    /// Nur die Drossel liest die Warteschlange
+    aenderungen_rx: Mutex<mpsc::UnboundedReceiver<UserId>>,
+    /// Erst mit einer Drossel werden Wechsel eingereiht
+    abonniert: AtomicBool,
+}
+
+/// Sprechzustand pro Benutzer (thread-safe, Clone teilt den Zustand)
+#[derive(Clone)]
+pub struct SprecherTracker {
+    inner: Arc<TrackerInner>,
+}
+
+impl Default for SprecherTracker {
+    fn default() -> Self {
+        Self::neu()
+    }
+}
+
+impl SprecherTracker {
+    /// Erstellt einen leeren Tracker mit Standard-Nachlaufzeit
+    pub fn neu() -> Self {
+        Self::mit_nachlauf(SPRECH_NACHLAUF)
+    }
+
+    /// Erstellt einen leeren Tracker mit eigener Nachlaufzeit
+    pub fn mit_nachlauf(nachlauf: Duration) -> Self {
+        let (aenderungen_tx, aenderungen_rx) = mpsc::unbounded_channel();
+        Self {
+            inner: Arc::new(TrackerInner {
+                eintraege: DashMap::new(),
+                nachlauf,
+                basis: Instant::now(),
+                aenderungen_tx,
+                aenderungen_rx: Mutex::new(aenderungen_rx),
+                abonniert: AtomicBool::new(false),
+            }),
+        }
+    }
+
+    /// Loest den Eintrag eines Benutzers fuer seine Voice-Sitzung auf
+    ///
+    /// Einmal bei der Registrierung aufrufen; die Sitzung verbucht danach
+    /// Pakete ohne Zugriff auf die Map.
+    pub fn sitzung(&self, user_id: UserId) -> SprecherSitzung {
+        let eintrag = self.inner.eintraege.entry(user_id).or_insert_with(|| {
+            Arc::new(SprecherEintrag {
+                spricht: AtomicBool::new(false),
+                letztes_paket_ms: AtomicU64::new(0),
+            })
+        });
+        SprecherSitzung {
+            tracker: self.clone(),
+            user_id,
+            eintrag: Arc::clone(&eintrag),
+        }
+    }
+
+    /// Verbucht ein Sprachpaket ohne aufgeloeste Sitzung
+    ///
+    /// `stop` beendet die Sprechphase sofort, jedes andere Paket beginnt oder
+    /// verlaengert sie.
+    pub fn paket(&self, user_id: UserId, stop: bool) {
+        self.paket_um(user_id, stop, Instant::now());
+    }
+
+    /// Verbucht ein Sprachpaket zu einem bestimmten Zeitpunkt
+    pub fn paket_um(&self, user_id: UserId, stop: bool, zeitpunkt: Instant) {
+        self.sitzung(user_id).paket_um(stop, zeitpunkt);
+    }
+
+    /// Spricht der Benutzer zum Zeitpunkt `jetzt` (inklusive Nachlauf)?
+    pub fn spricht(&self, user_id: &UserId, jetzt: Instant) -> bool {
+        self.inner
+            .eintraege
+            .get(user_id)
+            .is_some_and(|e| self.spricht_eintrag(&e, jetzt))
+    }
+
+    /// Filtert die sprechenden Benutzer aus `user_ids`
+    pub fn sprechende<'a>(
+        &self,
+        user_ids: impl IntoIterator<Item = &'a UserId>,
+        jetzt: Instant,
+    ) -> Vec<UserId> {
+        user_ids
+            .into_iter()
+            .filter(|u| self.spricht(u, jetzt))
+            .copied()
+            .collect()
+    }
+
+    /// Entfernt einen Benutzer (Voice getrennt); eine laufende Sprechphase
+    /// wird als beendet gemeldet
+    pub fn entfernen(&self, user_id: &UserId) {
+        if let Some((_, eintrag)) = self.inner.eintraege.remove(user_id) {
+            if eintrag.spricht.load(Ordering::Relaxed) {
+                self.wechsel_melden(*user_id);
+            }
+        }
+    }
+
+    /// Beendet Sprechphasen, deren Nachlauf abgelaufen ist
+    ///
+    /// Laeuft im Task der Drossel, nicht im Empfangspfad.
+    fn nachlauf_pruefen(&self, jetzt: Instant) {
+        for eintrag in self.inner.eintraege.iter() {
+            if eintrag.spricht.load(Ordering::Relaxed) && !self.spricht_eintrag(&eintrag, jetzt) {
+                eintrag.spricht.store(false, Ordering::Relaxed);
+                self.wechsel_melden(*eintrag.key());
+            }
+        }
+    }
+
+    /// Uebernimmt alle gemeldeten Wechsel aus der Warteschlange
+    fn aenderungen_abholen(&self, ziel: &mut HashSet<UserId>) {
+        let mut rx = self.inner.aenderungen_rx.lock();
+        while let Ok(user_id) = rx.try_recv() {
+            ziel.insert(user_id);
+        }
+    }
+
+    fn spricht_eintrag(&self, eintrag: &SprecherEintrag, jetzt: Instant) -> bool {
+        if !eintrag.spricht.load(Ordering::Relaxed) {
+            return false;
+        }
+        let letztes = Duration::from_millis(eintrag.letztes_paket_ms.load(Ordering::Relaxed));
+        jetzt.saturating_duration_since(self.inner.basis) < letztes + self.inner.nachlauf
+    }
+
+    fn wechsel_melden(&self, user_id: UserId) {
+        if !self.inner.abonniert.load(Ordering::Relaxed) {
+            return;
+        }
+        // Der Empfaenger lebt so lange wie der Tracker
+        let _ = self.inner.aenderungen_tx.send(user_id);
+    }
+
+    fn ms_seit_basis(&self, zeitpunkt: Instant) -> u64 {
+        zeitpunkt
+            .saturating_duration_since(self.inner.basis)
+            .as_millis() as u64
+    }
+}
+
+/// Sprechzustand einer Voice-Sitzung (siehe [`SprecherTracker::sitzung`])
+///
+/// Haelt den Eintrag direkt; Clone teilt ihn.
+#[derive(Clone)]
+pub struct SprecherSitzung {
+    tracker: SprecherTracker,
+    user_id: UserId,
+    eintrag: Arc<SprecherEintrag>,
+}
+
+impl SprecherSitzung {
+    /// Verbucht ein Sprachpaket (Hot Path, nur atomare Zugriffe)
+    ///
+    /// `stop` beendet die Sprechphase sofort, jedes andere Paket beginnt oder
+    /// verlaengert sie.
+    pub fn paket(&self, stop: bool) {
+        self.paket_um(stop, Instant::now());
+    }
+
+    /// Verbucht ein Sprachpaket zu einem bestimmten Zeitpunkt
+    pub fn paket_um(&self, stop: bool, zeitpunkt: Instant) {
+        let (tracker, eintrag) = (&self.tracker, &self.eintrag);
+        if stop {
+            if eintrag.spricht.load(Ordering::Relaxed) {
+                eintrag.spricht.store(false, Ordering::Relaxed);
+                tracker.wechsel_melden(self.user_id);
+            }
+            return;
+        }
+        eintrag
+            .letztes_paket_ms
+            .store(tracker.ms_seit_basis(zeitpunkt), Ordering::Relaxed);
+        if !eintrag.spricht.load(Ordering::Relaxed) {
+            eintrag.spricht.store(true, Ordering::Relaxed);
+            tracker.wechsel_melden(self.user_id);
+        }
+    }
+}
+
+/// Gemeldeter Wechsel des Sprechzustands
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SprecherAenderung {
+    pub user_id: UserId,
+    pub spricht: bool,
+}
+
+/// Fasst Wechsel zusammen und begrenzt die Meldungen pro Benutzer
+///
+/// Gehoert genau einem Task; der Zustand wird nicht geteilt.
+pub struct SprecherDrossel {
+    tracker: SprecherTracker,
+    abstand: Duration,
+    /// Benutzer mit noch nicht gemeldetem Wechsel
+    offen: HashSet<UserId>,
+    /// Zuletzt gemeldeter Zustand und Zeitpunkt der Meldung
+    gemeldet: HashMap<UserId, (bool, Instant)>,
+}
+
+impl SprecherDrossel {
+    /// Erstellt die Drossel eines Trackers mit dem Mindestabstand `abstand`
+    /// pro Benutzer
+    ///
+    /// Erst ab jetzt reiht der Tracker Wechsel ein.
+    pub fn neu(tracker: SprecherTracker, abstand: Duration) -> Self {
+        tracker.inner.abonniert.store(true, Ordering::Relaxed);
+        Self {
+            tracker,
+            abstand,
+            offen: HashSet::new(),
+            gemeldet: HashMap::new(),
+        }
+    }
+
+    /// Leert die Warteschlange und gibt die zu meldenden Wechsel zurueck
+    ///
+    /// Ein Wechsel, der zum zuletzt gemeldeten Zustand zurueckfuehrt, entfaellt.
+    /// Liegt die letzte Meldung weniger als `abstand` zurueck, wird der
+    /// Wechsel bis zur naechsten Abfrage zurueckgestellt.
+    pub fn abfragen(&mut self, jetzt: Instant) -> Vec<SprecherAenderung> {
+        let tracker = &self.tracker;
+        tracker.nachlauf_pruefen(jetzt);
+        tracker.aenderungen_abholen(&mut self.offen);
+
+        let mut aenderungen = Vec::new();
+        self.offen.retain(|user_id| {
+            let spricht = tracker.spricht(user_id, jetzt);
+            let zuletzt = self.gemeldet.get(user_id).copied();
+            if zuletzt.map_or(!spricht, |(z, _)| z == spricht) {
+                return false;
+            }
+            if zuletzt.is_some_and(|(_, um)| jetzt.saturating_duration_since(um) < self.abstand) {
+                return true;
+            }
+            self.gemeldet.insert(*user_id, (spricht, jetzt));
+            aenderungen.push(SprecherAenderung {
+                user_id: *user_id,
+                spricht,
+            });
+            false
+        });
+
+        // Verstummte Benutzer ohne ausstehenden Wechsel vergessen
+        let abstand = self.abstand;
+        let offen = &self.offen;
+        self.gemeldet.retain(|user_id, (spricht, um)| {
+            *spricht || offen.contains(user_id) || jetzt.saturating_duration_since(*um) < abstand
+        });
+        aenderungen
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ms(n: u64) -> Duration {
+        Duration::from_millis(n)
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn nachlauf_nach_letztem_paket() {
+        let tracker = SprecherTracker::mit_nachlauf(ms(500));
+        let user = UserId::new();
+        assert!(!tracker.spricht(&user, Instant::now()));
+
+        tracker.paket(user, false);
+        tokio::time::advance(ms(300)).await;
+        tracker.paket(user, false);
+        assert!(tracker.spricht(&user, Instant::now()));
+
+        // Nachlauf zaehlt ab dem letzten Paket
+        tokio::time::advance(ms(499)).await;
+        assert!(tracker.spricht(&user, Instant::now()));
+        tokio::time::advance(ms(1)).await;
+        assert!(!tracker.spricht(&user, Instant::now()));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn stop_flag_beendet_sofort() {
+        let tracker = SprecherTracker::neu();
+        let user = UserId::new();
+        tracker.paket(user, false);
+        tracker.paket(user, true);
+        assert!(!tracker.spricht(&user, Instant::now()));
+        assert_eq!(tracker.sprechende([&user], Instant::now()), vec![]);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn drossel_meldet_start_und_ablauf_des_nachlaufs() {
+        let tracker = SprecherTracker::mit_nachlauf(ms(500));
+        let mut drossel = SprecherDrossel::neu(tracker.clone(), ms(300));
+        let user = UserId::new();
+
+        tracker.paket(user, false);
+        assert_eq!(
+            drossel.abfragen(Instant::now()),
+            vec![SprecherAenderung {
+                user_id: user,
+                spricht: true
+            }]
+        );
+        // Weitere Pakete sind kein Wechsel
+        tokio::time::advance(ms(100)).await;
+        tracker.paket(user, false);
+        assert!(drossel.abfragen(Instant::now()).is_empty());
+
+        // Ohne Pakete endet die Phase erst nach dem Nachlauf
+        tokio::time::advance(ms(450)).await;
+        assert!(drossel.abfragen(Instant::now()).is_empty());
+        tokio::time::advance(ms(50)).await;
+        assert_eq!(
+            drossel.abfragen(Instant::now()),
+            vec![SprecherAenderung {
+                user_id: user,
+                spricht: false
+            }]
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn flattern_wird_zusammengefasst() {
+        let tracker = SprecherTracker::neu();
+        let mut drossel = SprecherDrossel::neu(tracker.clone(), ms(300));
+        let user = UserId::new();
+
+        tracker.paket(user, false);
+        assert_eq!(drossel.abfragen(Instant::now()).len(), 1);
+
+        // Stop/Start/Stop/Start innerhalb des Abstands: kein Wechsel sichtbar
+        for _ in 0..3 {
+            tokio::time::advance(ms(20)).await;
+            tracker.paket(user, true);
+            tracker.paket(user, false);
+        }
+        tokio::time::advance(ms(20)).await;
+        assert!(drossel.abfragen(Instant::now()).is_empty());
+
+        // Stop kurz nach der letzten Meldung wird zurueckgestellt ...
+        tracker.paket(user, true);
+        assert!(drossel.abfragen(Instant::now()).is_empty());
+        tokio::time::advance(ms(100)).await;
+        assert!(drossel.abfragen(Instant::now()).is_empty());
+
+        // ... und nach Ablauf des Abstands genau einmal gemeldet
+        tokio::time::advance(ms(200)).await;
+        assert_eq!(
+            drossel.abfragen(Instant::now()),
+            vec![SprecherAenderung {
+                user_id: user,
+                spricht: false
+            }]
+        );
+        tokio::time::advance(ms(300)).await;
+        assert!(drossel.abfragen(Instant::now()).is_empty());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn kurzes_sprechen_innerhalb_des_abstands() {
+        let tracker = SprecherTracker::neu();
+        let mut drossel = SprecherDrossel::neu(tracker.clone(), ms(300));
+        let user = UserId::new();
+
+        // Start und Stop zwischen zwei Abfragen: nichts zu melden
+        tracker.paket(user, false);
+        tracker.paket(user, true);
+        assert!(drossel.abfragen(Instant::now()).is_empty());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn entfernen_beendet_sprechphase() {
+        let tracker = SprecherTracker::neu();
+        let mut drossel = SprecherDrossel::neu(tracker.clone(), ms(300));
+        let user = UserId::new();
+
+        tracker.paket(user, false);
+        drossel.abfragen(Instant::now());
+        tokio::time::advance(ms(300)).await;
+        tracker.entfernen(&user);
+        assert_eq!(
+            drossel.abfragen(Instant::now()),
+            vec![SprecherAenderung {
+                user_id: user,
+                spricht: false
+            }]
+        );
+    }
+}
@@ -1,17 +1,25 @@
 //! Voice-State – In-Memory Zustand aller aktiven Voice-Sessions
 //!
 //! Verwaltet pro Client:
-//! - SSRC und UDP-Endpunkt
+//! - SSRC und UDP-Endpunkt (per Hello bestaetigt, siehe [`VoiceState::hello_pruefen`])
 //! - Channel-Zugehoerigkeit
 //! - Codec-Konfiguration
-//! - Speaking-Status
-//! - Netzwerk-Statistiken
+//! - Speaking-Status und Sendeerlaubnis (Nur-Zuhoeren)
+//! - Netzwerk-Statistiken und geschaetzte Sprachqualitaet (MOS)
+//! - Frische-Pruefung der eingehenden Pakete
+//! - Aktive Sprecher pro Kanal (siehe [`crate::sprecher`])
 //!
 //! Thread-safe durch DashMap (lock-free concurrent HashMap).
 
+use crate::congestion::BitrateRegelung;
+use crate::frische::{Frische, FrischePruefung, Takt};
+use crate::sprecher::{SprecherSitzung, SprecherTracker};
+use crate::telemetry::mos::{self, MosEingabe};
 use dashmap::DashMap;
 use speakeasy_core::types::{ChannelId, UserId};
 use speakeasy_protocol::codec::OpusConfig;
+use speakeasy_protocol::voice::{HelloPaket, SequenzStatistik, VoicePacketHeader};
+use std::collections::HashMap;
 use std::net::SocketAddr;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
@@ -20,6 +28,9 @@ use std::time::{Duration, Instant};
 // ClientVoiceState
 // ---------------------------------------------------------------------------
 
+/// Start-Bitrate der Regelung (kbps), entspricht dem Standard-Preset der Clients
+pub const START_BITRATE_KBPS: u16 = 64;
+
 /// Zustand eines einzelnen verbundenen Voice-Clients
 #[derive(Debug, Clone)]
 pub struct ClientVoiceState {
@@ -27,24 +38,46 @@ pub struct ClientVoiceState {
     pub user_id: UserId,
     /// Synchronisation Source – eindeutige Senderkennung (aus dem UDP-Header)
     pub ssrc: u32,
-    /// UDP-Endpunkt des Clients
+    /// UDP-Endpunkt des Clients (vor dem Hello nur vorlaeufig)
     pub udp_endpunkt: SocketAddr,
+    /// Der Client hat sich von `udp_endpunkt` mit einem gueltigen Hello
+    /// gemeldet; erst dann werden Pakete von dort angenommen
+    pub endpunkt_bestaetigt: bool,
+    /// Nonce fuer das Hello (`None` = Endpunkt ohne Hello vorgegeben)
+    pub hello_nonce: Option<Vec<u8>>,
+    /// Zuletzt angenommener Hello-Versuch zur aktuellen Nonce
+    /// (`None` = Nonce noch unverbraucht)
+    pub hello_versuch: Option<u32>,
     /// Aktueller Voice-Kanal (None wenn nicht in einem Kanal)
     pub kanal_id: Option<ChannelId>,
     /// Vereinbarte Codec-Konfiguration
     pub codec_config: Option<OpusConfig>,
     /// Spricht der Client gerade?
     pub spricht: bool,
+    /// Darf der Client senden? (`false` = Nur-Zuhoeren, Pakete werden verworfen)
+    pub darf_senden: bool,
+    /// Verworfene Pakete, die trotz Nur-Zuhoeren eintrafen
+    pub nur_hoeren_verworfen: u64,
     /// Zeitpunkt des letzten empfangenen Pakets
     pub letztes_paket: Instant,
     /// Letzte gemessene RTT in ms
     pub rtt_ms: u32,
-    /// Paketverlust-Rate (0.0–1.0)
+    /// Paketverlust-Rate auf dem Weg Client -> Server (0.0–1.0, Uplink)
     pub verlust_rate: f64,
+    /// Vom Client gemeldete Verlust-Rate auf dem Weg Server -> Client (Downlink)
+    pub downlink_verlust_rate: f64,
+    /// Empfangsstatistik des Servers fuer die Pakete dieses Clients
+    pub uplink: SequenzStatistik,
+    /// Ankunftskadenz fuer die Frische-Pruefung
+    pub frische: FrischePruefung,
+    /// Wegen Verspaetung nicht weitergeleitete Pakete (kein Uplink-Verlust)
+    pub veraltet_verworfen: u64,
     /// Gemessener Jitter in Ticks
     pub jitter_ticks: u32,
-    /// Empfohlene Bitrate (kbps) – kann vom Congestion Controller angepasst werden
-    pub empfohlene_bitrate_kbps: u16,
+    /// Bitrate-Empfehlung, geregelt aus den Statistik-Berichten des Clients
+    pub bitrate: BitrateRegelung,
+    /// Zuletzt geschaetzte Sprachqualitaet (1.0–4.5, `None` = noch kein Bericht)
+    pub mos: Option<f64>,
 }
 
 impl ClientVoiceState {
@@ -54,15 +87,41 @@ impl ClientVoiceState {
             user_id,
             ssrc,
             udp_endpunkt,
+            endpunkt_bestaetigt: true,
+            hello_nonce: None,
+            hello_versuch: None,
             kanal_id: None,
             codec_config: None,
             spricht: false,
+            darf_senden: true,
+            nur_hoeren_verworfen: 0,
             letztes_paket: Instant::now(),
             rtt_ms: 0,
             verlust_rate: 0.0,
+            downlink_verlust_rate: 0.0,
+            uplink: SequenzStatistik::default(),
+            frische: FrischePruefung::neu(),
+            veraltet_verworfen: 0,
             jitter_ticks: 0,
-            empfohlene_bitrate_kbps: 64,
+            bitrate: BitrateRegelung::neu(START_BITRATE_KBPS),
+            mos: None,
+        }
+    }
+
+    /// Schaetzt die Sprachqualitaet aus Sicht des Servers
+    ///
+    /// Ersatz fuer Clients, die keine eigene Note melden: Puffertiefe und
+    /// Jitter-Buffer-Verwurf des Clients sind unbekannt, Uplink- und
+    /// Downlink-Verlust werden zusammengefasst.
+    pub fn mos_schaetzen(&self) -> f64 {
+        let verlust = 1.0 - (1.0 - self.verlust_rate) * (1.0 - self.downlink_verlust_rate);
+        MosEingabe {
+            einweg_verzoegerung_ms: mos::einweg_verzoegerung_ms(self.rtt_ms, 0),
+            verlust_rate: verlust,
+            verwurf_rate: 0.0,
+            bitrate_kbps: f64::from(self.bitrate.bitrate_kbps()),
         }
+        .mos()
     }
 
     /// Prueft ob der Client als inaktiv gilt (kein Paket seit `timeout`)
@@ -80,6 +139,21 @@ impl ClientVoiceState {
 // VoiceState
 // ---------------------------------------------------------------------------
 
+/// Ergebnis von [`VoiceState::hello_pruefen`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HelloErgebnis {
+    /// Endpunkt bestaetigt
+    Bestaetigt(UserId),
+    /// Keine Registrierung mit dieser SSRC
+    UnbekannteSsrc,
+    /// HMAC passt nicht zur Nonce (oder Client ohne Nonce)
+    Ungueltig,
+    /// Versuch nicht groesser als der zuletzt angenommene (Wiederholung)
+    Wiederholt,
+    /// Endpunkt mit dieser Nonce bereits bestaetigt, Absender weicht ab
+    EndpunktGebunden,
+}
+
 /// Timeout fuer inaktive Clients (30 Sekunden ohne Paket)
 pub const CLIENT_TIMEOUT: Duration = Duration::from_secs(30);
 
@@ -97,41 +171,181 @@ struct VoiceStateInner {
     clients: DashMap<UserId, ClientVoiceState>,
     /// SSRC -> UserId Mapping fuer schnellen Lookup aus UDP-Paketen
     ssrc_index: DashMap<u32, UserId>,
-    /// UDP-Endpunkt -> UserId Mapping
-    endpunkt_index: DashMap<SocketAddr, UserId>,
+    /// UDP-Endpunkt -> Absender Mapping (Hot Path der Sprachpakete)
+    endpunkt_index: DashMap<SocketAddr, Absender>,
+    /// Sprechzustand mit Nachlauf (kann mit dem Signaling geteilt werden)
+    sprecher: SprecherTracker,
+}
+
+/// Eintrag im Endpunkt-Index
+///
+/// Der Sprechzustand wird bei der Registrierung aufgeloest, damit der
+/// Empfangspfad pro Paket keinen weiteren Map-Zugriff braucht.
+struct Absender {
+    user_id: UserId,
+    sprecher: SprecherSitzung,
 }
 
 impl VoiceState {
     /// Erstellt einen neuen leeren VoiceState
     pub fn neu() -> Self {
+        Self::mit_sprecher(SprecherTracker::neu())
+    }
+
+    /// Erstellt einen leeren VoiceState mit gemeinsamem Sprecher-Tracker
+    pub fn mit_sprecher(sprecher: SprecherTracker) -> Self {
         Self {
             inner: Arc::new(VoiceStateInner {
                 clients: DashMap::new(),
                 ssrc_index: DashMap::new(),
                 endpunkt_index: DashMap::new(),
+                sprecher,
             }),
         }
     }
 
-    /// Registriert einen neuen Client
+    /// Sprechzustand aller Benutzer
+    pub fn sprecher(&self) -> &SprecherTracker {
+        &self.inner.sprecher
+    }
+
+    /// Gibt die gerade sprechenden Clients eines Kanals zurueck
+    pub fn aktive_sprecher(&self, kanal_id: &ChannelId) -> Vec<UserId> {
+        let mitglieder = self.clients_in_kanal(kanal_id);
+        self.inner
+            .sprecher
+            .sprechende(&mitglieder, tokio::time::Instant::now())
+    }
+
+    /// Registriert einen neuen Client mit bekanntem UDP-Endpunkt
+    ///
+    /// Eine fruehere Registrierung desselben Clients (erneuter VoiceInit)
+    /// wird ersetzt, ihre SSRC und ihr Endpunkt werden freigegeben.
     pub fn client_registrieren(&self, user_id: UserId, ssrc: u32, udp_endpunkt: SocketAddr) {
-        let state = ClientVoiceState::neu(user_id, ssrc, udp_endpunkt);
+        self.einfuegen(ClientVoiceState::neu(user_id, ssrc, udp_endpunkt));
+    }
+
+    /// Registriert einen neuen Client, dessen UDP-Endpunkt erst ein Hello
+    /// bestaetigt
+    ///
+    /// `vorlaeufig` (z.B. aus der TCP-Verbindung) dient nur der Anzeige;
+    /// Pakete von dort werden bis zum Hello verworfen.
+    pub fn client_anmelden(
+        &self,
+        user_id: UserId,
+        ssrc: u32,
+        vorlaeufig: SocketAddr,
+        hello_nonce: Vec<u8>,
+    ) {
+        let mut state = ClientVoiceState::neu(user_id, ssrc, vorlaeufig);
+        state.endpunkt_bestaetigt = false;
+        state.hello_nonce = Some(hello_nonce);
+        self.einfuegen(state);
+    }
+
+    fn einfuegen(&self, state: ClientVoiceState) {
+        let (user_id, ssrc, udp_endpunkt) = (state.user_id, state.ssrc, state.udp_endpunkt);
+        if let Some(alt) = self.client_entfernen(&user_id) {
+            tracing::debug!(user_id = %user_id, alte_ssrc = alt.ssrc, "Client neu registriert");
+        }
+        if state.endpunkt_bestaetigt {
+            self.endpunkt_eintragen(udp_endpunkt, user_id);
+        }
+        let bestaetigt = state.endpunkt_bestaetigt;
         self.inner.clients.insert(user_id, state);
         self.inner.ssrc_index.insert(ssrc, user_id);
-        self.inner.endpunkt_index.insert(udp_endpunkt, user_id);
         tracing::info!(
             user_id = %user_id,
             ssrc,
             endpunkt = %udp_endpunkt,
+            bestaetigt,
             "Client registriert"
         );
     }
 
+    /// Setzt eine bestehende Registrierung fort (wiederholter VoiceInit)
+    ///
+    /// Behaelt SSRC und bestaetigten UDP-Endpunkt, uebernimmt die neue
+    /// Hello-Nonce und setzt die Uplink-Statistik zurueck, da der Client
+    /// seine Sequenz neu beginnen kann. Gibt die SSRC zurueck, `None` ohne
+    /// Registrierung.
+    pub fn sitzung_fortsetzen(&self, user_id: &UserId, hello_nonce: Vec<u8>) -> Option<u32> {
+        let mut state = self.inner.clients.get_mut(user_id)?;
+        state.hello_nonce = Some(hello_nonce);
+        state.hello_versuch = None;
+        state.uplink = SequenzStatistik::default();
+        state.frische.zuruecksetzen();
+        state.paket_empfangen();
+        tracing::debug!(user_id = %user_id, ssrc = state.ssrc, "Voice-Sitzung fortgesetzt");
+        Some(state.ssrc)
+    }
+
+    /// Prueft ein Hello und uebernimmt bei Erfolg die Absenderadresse
+    ///
+    /// Je Nonce werden nur steigende Versuche angenommen, ein mitgeschnittenes
+    /// Hello ist damit verbraucht. Die beobachtete Adresse ersetzt einen
+    /// bereits bestaetigten Endpunkt nur mit frischer Nonce (neues
+    /// NAT-Mapping meldet der Client ueber einen erneuten `VoiceInit`).
+    pub fn hello_pruefen(&self, hello: &HelloPaket, absender: SocketAddr) -> HelloErgebnis {
+        let Some(user_id) = self.user_id_von_ssrc(hello.ssrc) else {
+            return HelloErgebnis::UnbekannteSsrc;
+        };
+        let Some(mut state) = self.inner.clients.get_mut(&user_id) else {
+            return HelloErgebnis::UnbekannteSsrc;
+        };
+        if !state
+            .hello_nonce
+            .as_deref()
+            .is_some_and(|n| hello.pruefen(n))
+        {
+            return HelloErgebnis::Ungueltig;
+        }
+        if state.hello_versuch.is_some_and(|v| hello.versuch <= v) {
+            return HelloErgebnis::Wiederholt;
+        }
+        let verlegen = state.endpunkt_bestaetigt && state.udp_endpunkt != absender;
+        if verlegen && state.hello_versuch.is_some() {
+            return HelloErgebnis::EndpunktGebunden;
+        }
+        state.hello_versuch = Some(hello.versuch);
+
+        if !state.endpunkt_bestaetigt || verlegen {
+            if state.endpunkt_bestaetigt {
+                self.inner
+                    .endpunkt_index
+                    .remove_if(&state.udp_endpunkt, |_, a| a.user_id == user_id);
+            }
+            self.endpunkt_eintragen(absender, user_id);
+            tracing::info!(
+                user_id = %user_id,
+                ssrc = hello.ssrc,
+                endpunkt = %absender,
+                "UDP-Endpunkt per Hello bestaetigt"
+            );
+            state.udp_endpunkt = absender;
+            state.endpunkt_bestaetigt = true;
+        }
+        state.paket_empfangen();
+        HelloErgebnis::Bestaetigt(user_id)
+    }
+
+    fn endpunkt_eintragen(&self, endpunkt: SocketAddr, user_id: UserId) {
+        let sprecher = self.inner.sprecher.sitzung(user_id);
+        self.inner
+            .endpunkt_index
+            .insert(endpunkt, Absender { user_id, sprecher });
+    }
+
     /// Entfernt einen Client und bereinigt alle Indizes
     pub fn client_entfernen(&self, user_id: &UserId) -> Option<ClientVoiceState> {
         if let Some((_, state)) = self.inner.clients.remove(user_id) {
             self.inner.ssrc_index.remove(&state.ssrc);
-            self.inner.endpunkt_index.remove(&state.udp_endpunkt);
+            if state.endpunkt_bestaetigt {
+                self.inner
+                    .endpunkt_index
+                    .remove_if(&state.udp_endpunkt, |_, a| a.user_id == *user_id);
+            }
+            self.inner.sprecher.entfernen(user_id);
             tracing::info!(user_id = %user_id, "Client entfernt");
             Some(state)
         } else {
@@ -144,9 +358,30 @@ impl VoiceState {
         self.inner.ssrc_index.get(&ssrc).map(|r| *r)
     }
 
+    /// Prueft ob eine SSRC bereits einem Client gehoert
+    pub fn ssrc_belegt(&self, ssrc: u32) -> bool {
+        self.inner.ssrc_index.contains_key(&ssrc)
+    }
+
+    /// Gibt die SSRC eines registrierten Clients zurueck
+    pub fn ssrc_von_user(&self, user_id: &UserId) -> Option<u32> {
+        self.inner.clients.get(user_id).map(|s| s.ssrc)
+    }
+
     /// Sucht UserId anhand des UDP-Endpunkts
     pub fn user_id_von_endpunkt(&self, endpunkt: &SocketAddr) -> Option<UserId> {
-        self.inner.endpunkt_index.get(endpunkt).map(|r| *r)
+        self.inner.endpunkt_index.get(endpunkt).map(|a| a.user_id)
+    }
+
+    /// Sucht Absender und Sprechzustand anhand des UDP-Endpunkts (Hot Path)
+    pub fn absender_von_endpunkt(
+        &self,
+        endpunkt: &SocketAddr,
+    ) -> Option<(UserId, SprecherSitzung)> {
+        self.inner
+            .endpunkt_index
+            .get(endpunkt)
+            .map(|a| (a.user_id, a.sprecher.clone()))
     }
 
     /// Gibt eine Referenz auf den Client-State zurueck (shared lock)
@@ -180,11 +415,70 @@ impl VoiceState {
         self.client_aktualisieren(user_id, |s| s.spricht = spricht);
     }
 
+    /// Setzt die Sendeerlaubnis (Nur-Zuhoeren = `false`)
+    ///
+    /// Beendet eine laufende Sprechphase, wenn das Senden entzogen wird.
+    pub fn senden_erlauben(&self, user_id: &UserId, erlaubt: bool) {
+        self.client_aktualisieren(user_id, |s| {
+            s.darf_senden = erlaubt;
+            s.spricht &= erlaubt;
+        });
+    }
+
+    /// Verwirft ein Paket, wenn der Client nicht senden darf
+    ///
+    /// Gibt `true` zurueck, wenn das Paket verworfen (und gezaehlt) wurde.
+    /// Der Normalfall kommt mit einem Lesezugriff aus.
+    pub fn sendeverbot_verbuchen(&self, user_id: &UserId) -> bool {
+        let gesperrt = self
+            .inner
+            .clients
+            .get(user_id)
+            .is_some_and(|s| !s.darf_senden);
+        if gesperrt {
+            self.client_aktualisieren(user_id, |s| {
+                s.nur_hoeren_verworfen += 1;
+                s.paket_empfangen();
+            });
+        }
+        gesperrt
+    }
+
     /// Aktualisiert den Paket-Zeitstempel (beim Empfang eines Pakets)
     pub fn paket_zeitstempel_aktualisieren(&self, user_id: &UserId) {
         self.client_aktualisieren(user_id, |s| s.paket_empfangen());
     }
 
+    /// Verbucht ein empfangenes Paket in der Uplink-Statistik des Clients
+    pub fn uplink_paket_verbuchen(&self, user_id: &UserId, sequenz: u32) {
+        self.client_aktualisieren(user_id, |s| {
+            s.paket_empfangen();
+            s.uplink.paket(sequenz);
+        });
+    }
+
+    /// Prueft ob ein Paket des Clients noch frisch genug zum Weiterleiten ist
+    ///
+    /// Der erwartete Takt stammt aus der vereinbarten Codec-Konfiguration.
+    /// Veraltete Pakete werden pro Client gezaehlt. Ohne Registrierung gilt
+    /// das Paket als frisch.
+    pub fn frische_pruefen(
+        &self,
+        user_id: &UserId,
+        header: &VoicePacketHeader,
+        ttl: Duration,
+    ) -> Frische {
+        let mut frische = Frische::Frisch;
+        self.client_aktualisieren(user_id, |s| {
+            let takt = Takt::aus_codec(s.codec_config.as_ref());
+            frische = s.frische.pruefen(header, Instant::now(), takt, ttl);
+            if !frische.weiterleiten() {
+                s.veraltet_verworfen += 1;
+            }
+        });
+        frische
+    }
+
     /// Gibt alle Clients in einem bestimmten Kanal zurueck
     ///
     /// Iteriert ueber DashMap – wird nicht im Hot Path verwendet
@@ -212,6 +506,24 @@ impl VoiceState {
             .collect()
     }
 
+    /// Mittlere Sprachqualitaet je Kanal
+    ///
+    /// Beruecksichtigt nur Clients, fuer die bereits eine Note vorliegt.
+    pub fn mos_pro_kanal(&self) -> Vec<(ChannelId, f64)> {
+        let mut summen: HashMap<ChannelId, (f64, u32)> = HashMap::new();
+        for client in self.inner.clients.iter() {
+            if let (Some(kanal_id), Some(mos)) = (client.kanal_id, client.mos) {
+                let eintrag = summen.entry(kanal_id).or_default();
+                eintrag.0 += mos;
+                eintrag.1 += 1;
+            }
+        }
+        summen
+            .into_iter()
+            .map(|(kanal_id, (summe, anzahl))| (kanal_id, summe / f64::from(anzahl)))
+            .collect()
+    }
+
     /// Bereinigt inaktive Clients (Timeout-Handling)
     ///
     /// Gibt die Liste der entfernten User-IDs zurueck.
@@ -297,6 +609,133 @@ mod tests {
         assert_eq!(state.client_anzahl(), 0);
     }
 
+    #[test]
+    fn sitzung_fortsetzen_behaelt_ssrc() {
+        let state = VoiceState::neu();
+        let uid = UserId::new();
+        assert_eq!(state.sitzung_fortsetzen(&uid, vec![1; 32]), None);
+
+        state.client_registrieren(uid, 9, test_endpunkt(10005));
+        state.uplink_paket_verbuchen(&uid, 100);
+        assert_eq!(state.sitzung_fortsetzen(&uid, vec![1; 32]), Some(9));
+
+        assert_eq!(state.client_anzahl(), 1);
+        assert_eq!(state.user_id_von_ssrc(9), Some(uid));
+        // Der bestaetigte Endpunkt gilt weiter, bis ein neues Hello eintrifft
+        assert_eq!(state.user_id_von_endpunkt(&test_endpunkt(10005)), Some(uid));
+        let client = state.client_state(&uid).unwrap();
+        assert_eq!(client.uplink, SequenzStatistik::default());
+        assert_eq!(client.hello_nonce.as_deref(), Some(&[1u8; 32][..]));
+    }
+
+    #[test]
+    fn hello_bestaetigt_beobachteten_endpunkt() {
+        let state = VoiceState::neu();
+        let uid = UserId::new();
+        let nonce = vec![5u8; 32];
+        // Vorlaeufiger Endpunkt aus der TCP-Verbindung, Port unbekannt
+        state.client_anmelden(uid, 0xAA, test_endpunkt(0), nonce.clone());
+        assert!(state.user_id_von_endpunkt(&test_endpunkt(0)).is_none());
+
+        // Falsche Nonce oder unbekannte SSRC aendern nichts
+        let falsch = HelloPaket::neu(0xAA, 0, &[6u8; 32]);
+        assert_eq!(
+            state.hello_pruefen(&falsch, test_endpunkt(10007)),
+            HelloErgebnis::Ungueltig
+        );
+        let fremd = HelloPaket::neu(0xBB, 0, &nonce);
+        assert_eq!(
+            state.hello_pruefen(&fremd, test_endpunkt(10007)),
+            HelloErgebnis::UnbekannteSsrc
+        );
+        assert!(state.user_id_von_endpunkt(&test_endpunkt(10007)).is_none());
+
+        // Hinter NAT: beobachtete Adresse statt ausgehandelter
+        let hello = HelloPaket::neu(0xAA, 1, &nonce);
+        assert_eq!(
+            state.hello_pruefen(&hello, test_endpunkt(10007)),
+            HelloErgebnis::Bestaetigt(uid)
+        );
+        assert_eq!(state.user_id_von_endpunkt(&test_endpunkt(10007)), Some(uid));
+        assert!(state.client_state(&uid).unwrap().endpunkt_bestaetigt);
+
+        // Neues NAT-Mapping mit frischer Nonce: der alte Endpunkt verliert
+        // seine Zuordnung
+        let frisch = vec![6u8; 32];
+        state.sitzung_fortsetzen(&uid, frisch.clone());
+        assert_eq!(
+            state.hello_pruefen(&HelloPaket::neu(0xAA, 0, &frisch), test_endpunkt(10008)),
+            HelloErgebnis::Bestaetigt(uid)
+        );
+        assert!(state.user_id_von_endpunkt(&test_endpunkt(10007)).is_none());
+        assert_eq!(state.user_id_von_endpunkt(&test_endpunkt(10008)), Some(uid));
+
+        state.client_entfernen(&uid);
+        assert!(state.user_id_von_endpunkt(&test_endpunkt(10008)).is_none());
+    }
+
+    #[test]
+    fn mitgeschnittenes_hello_verlegt_endpunkt_nicht() {
+        let state = VoiceState::neu();
+        let uid = UserId::new();
+        let nonce = vec![5u8; 32];
+        state.client_anmelden(uid, 0xAA, test_endpunkt(0), nonce.clone());
+        let erstes = HelloPaket::neu(0xAA, 0, &nonce);
+        let zweites = HelloPaket::neu(0xAA, 1, &nonce);
+        assert_eq!(
+            state.hello_pruefen(&zweites, test_endpunkt(10007)),
+            HelloErgebnis::Bestaetigt(uid)
+        );
+
+        // Wiederholung und aelterer Versuch sind verbraucht, auch von der
+        // bestaetigten Adresse
+        assert_eq!(
+            state.hello_pruefen(&zweites, test_endpunkt(10007)),
+            HelloErgebnis::Wiederholt
+        );
+        assert_eq!(
+            state.hello_pruefen(&erstes, test_endpunkt(20000)),
+            HelloErgebnis::Wiederholt
+        );
+        // Ein spaeterer Versuch von fremder Adresse verlegt ohne frische
+        // Nonce nichts
+        assert_eq!(
+            state.hello_pruefen(&HelloPaket::neu(0xAA, 2, &nonce), test_endpunkt(20000)),
+            HelloErgebnis::EndpunktGebunden
+        );
+        assert!(state.user_id_von_endpunkt(&test_endpunkt(20000)).is_none());
+        assert_eq!(state.user_id_von_endpunkt(&test_endpunkt(10007)), Some(uid));
+
+        // Spaeterer Versuch von der bestaetigten Adresse (verlorene
+        // Bestaetigung) wird erneut bestaetigt
+        assert_eq!(
+            state.hello_pruefen(&HelloPaket::neu(0xAA, 3, &nonce), test_endpunkt(10007)),
+            HelloErgebnis::Bestaetigt(uid)
+        );
+
+        // Nach neuem VoiceInit ist die alte Nonce wertlos
+        state.sitzung_fortsetzen(&uid, vec![6u8; 32]);
+        assert_eq!(
+            state.hello_pruefen(&HelloPaket::neu(0xAA, 4, &nonce), test_endpunkt(20000)),
+            HelloErgebnis::Ungueltig
+        );
+    }
+
+    #[test]
+    fn erneute_registrierung_gibt_alte_ssrc_frei() {
+        let state = VoiceState::neu();
+        let uid = UserId::new();
+
+        state.client_registrieren(uid, 7, test_endpunkt(10003));
+        state.client_registrieren(uid, 8, test_endpunkt(10004));
+
+        assert_eq!(state.client_anzahl(), 1);
+        assert!(!state.ssrc_belegt(7));
+        assert!(state.ssrc_belegt(8));
+        assert_eq!(state.ssrc_von_user(&uid), Some(8));
+        assert!(state.user_id_von_endpunkt(&test_endpunkt(10003)).is_none());
+    }
+
     #[test]
     fn kanal_setzen_und_abfragen() {
         let state = VoiceState::neu();
@@ -332,6 +771,36 @@ mod tests {
         assert_eq!(state.clients_in_kanal(&anderer_kanal).len(), 1);
     }
 
+    #[test]
+    fn mos_wird_je_kanal_gemittelt() {
+        let state = VoiceState::neu();
+        let kanal = ChannelId::new();
+
+        for (i, mos) in [Some(4.0), Some(3.0), None].into_iter().enumerate() {
+            let uid = UserId::new();
+            state.client_registrieren(uid, i as u32, test_endpunkt(10040 + i as u16));
+            state.kanal_setzen(&uid, Some(kanal));
+            state.client_aktualisieren(&uid, |c| c.mos = mos);
+        }
+        // Ohne Kanal zaehlt die Note nicht
+        let uid_ohne = UserId::new();
+        state.client_registrieren(uid_ohne, 50, test_endpunkt(10050));
+        state.client_aktualisieren(&uid_ohne, |c| c.mos = Some(1.0));
+
+        assert_eq!(state.mos_pro_kanal(), vec![(kanal, 3.5)]);
+    }
+
+    #[test]
+    fn server_schaetzung_sinkt_mit_verlust() {
+        let mut client = ClientVoiceState::neu(UserId::new(), 1, test_endpunkt(10060));
+        client.rtt_ms = 40;
+        let gut = client.mos_schaetzen();
+        assert!(gut > 4.3);
+
+        client.downlink_verlust_rate = 0.05;
+        assert!(client.mos_schaetzen() < gut - 0.5);
+    }
+
     #[test]
     fn speaking_status() {
         let state = VoiceState::neu();
@@ -347,6 +816,57 @@ mod tests {
         assert!(sprechende.is_empty());
     }
 
+    #[tokio::test(start_paused = true)]
+    async fn aktive_sprecher_pro_kanal() {
+        let state = VoiceState::neu();
+        let kanal = ChannelId::new();
+        let (a, b, fremd) = (UserId::new(), UserId::new(), UserId::new());
+        for (i, uid) in [a, b, fremd].into_iter().enumerate() {
+            state.client_registrieren(uid, i as u32, test_endpunkt(10060 + i as u16));
+        }
+        state.kanal_setzen(&a, Some(kanal));
+        state.kanal_setzen(&b, Some(kanal));
+        state.kanal_setzen(&fremd, Some(ChannelId::new()));
+
+        // Die bei der Registrierung aufgeloeste Sitzung teilt den Tracker
+        let (absender, sitzung) = state.absender_von_endpunkt(&test_endpunkt(10060)).unwrap();
+        assert_eq!(absender, a);
+        sitzung.paket(false);
+        state.sprecher().paket(fremd, false);
+        assert_eq!(state.aktive_sprecher(&kanal), vec![a]);
+
+        // Abmelden beendet die Sprechphase sofort
+        state.client_entfernen(&a);
+        assert!(state.aktive_sprecher(&kanal).is_empty());
+        assert!(!state.sprecher().spricht(&a, tokio::time::Instant::now()));
+
+        // Neue Registrierung, neue Sitzung
+        state.client_registrieren(a, 7, test_endpunkt(10070));
+        state.kanal_setzen(&a, Some(kanal));
+        let (_, sitzung) = state.absender_von_endpunkt(&test_endpunkt(10070)).unwrap();
+        sitzung.paket(false);
+        assert_eq!(state.aktive_sprecher(&kanal), vec![a]);
+    }
+
+    #[test]
+    fn nur_hoeren_verwirft_und_zaehlt() {
+        let state = VoiceState::neu();
+        let uid = UserId::new();
+        state.client_registrieren(uid, 1, test_endpunkt(10050));
+        assert!(!state.sendeverbot_verbuchen(&uid));
+
+        state.speaking_setzen(&uid, true);
+        state.senden_erlauben(&uid, false);
+        assert!(state.sprechende_clients().is_empty());
+        assert!(state.sendeverbot_verbuchen(&uid));
+        assert!(state.sendeverbot_verbuchen(&uid));
+        assert_eq!(state.client_state(&uid).unwrap().nur_hoeren_verworfen, 2);
+
+        state.senden_erlauben(&uid, true);
+        assert!(!state.sendeverbot_verbuchen(&uid));
+        assert!(!state.sendeverbot_verbuchen(&UserId::new()));
+    }
+
     #[test]
     fn clone_teilt_inneren_state() {
         let state1 = VoiceState::neu();
@@ -8,13 +8,25 @@
 //! - Jitter (Standardabweichung der Interarrival-Zeit)
 //! - Bitrate (Senden und Empfangen)
 //! - Jitter-Buffer-Fuellstand
+//! - Vom Kernel verworfene Datagramme des Voice-Sockets ([`SocketAbtaster`])
+//! - Jitter-Buffer-Statistik pro SSRC ([`VoiceMetricsCollector`])
+//! - Geschaetzte Sprachqualitaet als MOS-Note ([`mos`])
 //!
 //! ## Export
 //! Alle 5 Sekunden wird ein `TelemetrieSnapshot` erstellt, der ueber ein
 //! tokio-Kanal-Interface fuer Observability-Systeme verfuegbar gemacht wird.
 
+pub mod mos;
+
+use crate::jitter_buffer::JitterBufferStatistik;
+use crate::state::VoiceState;
+use crate::udp::VoiceServer;
 use dashmap::DashMap;
 use speakeasy_core::types::UserId;
+use speakeasy_protocol::socket_statistik::{
+    DropErkennung, SocketPuffer, SocketZaehler, ABHILFE_HINWEIS,
+};
+use std::sync::atomic::{AtomicU32, AtomicU64, AtomicUsize, Ordering};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 
@@ -303,6 +315,229 @@ impl VoiceTelemetry {
     }
 }
 
+// ---------------------------------------------------------------------------
+// Jitter-Buffer-Metriken pro SSRC
+// ---------------------------------------------------------------------------
+
+/// Zuletzt gemeldeter Stand des Jitter Buffers einer SSRC
+///
+/// Die Empfangsschleife schreibt nach jedem Paket (`Relaxed`), der
+/// Abtast-Task liest periodisch – keine Locks im Hot Path.
+#[derive(Debug, Default)]
+pub struct JitterZaehler {
+    empfangen: AtomicU64,
+    verloren: AtomicU64,
+    duplikate: AtomicU64,
+    jitter_ticks: AtomicU32,
+    fuellstand: AtomicUsize,
+}
+
+impl JitterZaehler {
+    /// Uebernimmt die aktuelle Statistik eines Jitter Buffers
+    pub fn melden(&self, statistik: &JitterBufferStatistik) {
+        self.empfangen.store(statistik.empfangen, Ordering::Relaxed);
+        self.verloren.store(statistik.verloren, Ordering::Relaxed);
+        self.duplikate.store(statistik.duplikate, Ordering::Relaxed);
+        self.jitter_ticks
+            .store(statistik.jitter_ticks, Ordering::Relaxed);
+        self.fuellstand
+            .store(statistik.fuellstand, Ordering::Relaxed);
+    }
+
+    /// Liest den gemeldeten Stand (nicht erfasste Felder bleiben 0)
+    pub fn lesen(&self) -> JitterBufferStatistik {
+        JitterBufferStatistik {
+            empfangen: self.empfangen.load(Ordering::Relaxed),
+            verloren: self.verloren.load(Ordering::Relaxed),
+            duplikate: self.duplikate.load(Ordering::Relaxed),
+            jitter_ticks: self.jitter_ticks.load(Ordering::Relaxed),
+            fuellstand: self.fuellstand.load(Ordering::Relaxed),
+            ..Default::default()
+        }
+    }
+}
+
+/// Abgetastete Jitter-Buffer-Statistik einer SSRC
+#[derive(Debug, Clone)]
+pub struct JitterMetrik {
+    pub ssrc: u32,
+    pub statistik: JitterBufferStatistik,
+}
+
+/// Ergebnis einer Abtastung des [`VoiceMetricsCollector`]
+#[derive(Debug, Clone, Default)]
+pub struct JitterMetrikSnapshot {
+    /// Stand aller bekannten SSRCs
+    pub ssrcs: Vec<JitterMetrik>,
+    /// Seit der letzten Abtastung abgemeldete SSRCs
+    pub entfernt: Vec<u32>,
+}
+
+/// Sammelt die Jitter-Buffer-Statistiken aller Absender fuer den Export
+///
+/// Der [`VoiceServer`] bildet pro SSRC den Jitter Buffer eines Empfaengers
+/// nach und meldet dessen Statistik an den zugehoerigen [`JitterZaehler`].
+/// Die Map selbst wird nur beim ersten Paket einer SSRC und beim Abtasten
+/// beruehrt.
+#[derive(Clone, Default)]
+pub struct VoiceMetricsCollector {
+    ssrcs: Arc<DashMap<u32, Arc<JitterZaehler>>>,
+}
+
+impl VoiceMetricsCollector {
+    /// Erstellt einen leeren Sammler
+    pub fn neu() -> Self {
+        Self::default()
+    }
+
+    /// Gibt die Zaehler einer SSRC zurueck und legt sie bei Bedarf an
+    pub fn zaehler(&self, ssrc: u32) -> Arc<JitterZaehler> {
+        Arc::clone(&self.ssrcs.entry(ssrc).or_default())
+    }
+
+    /// Gibt `true` zurueck solange der Sammler `zaehler` noch exportiert
+    ///
+    /// Nach dem Entfernen der SSRC haelt nur noch der Aufrufer die Zaehler.
+    pub fn ist_aktiv(zaehler: &Arc<JitterZaehler>) -> bool {
+        Arc::strong_count(zaehler) > 1
+    }
+
+    /// Entfernt eine SSRC aus dem Export
+    pub fn entfernen(&self, ssrc: u32) -> bool {
+        self.ssrcs.remove(&ssrc).is_some()
+    }
+
+    /// Gibt die Anzahl der erfassten SSRCs zurueck
+    pub fn ssrc_anzahl(&self) -> usize {
+        self.ssrcs.len()
+    }
+
+    /// Liest alle Zaehler; SSRCs, die `state` nicht mehr kennt, entfallen
+    pub fn abtasten(&self, state: &VoiceState) -> JitterMetrikSnapshot {
+        let mut entfernt = Vec::new();
+        self.ssrcs.retain(|ssrc, _| {
+            let belegt = state.ssrc_belegt(*ssrc);
+            if !belegt {
+                entfernt.push(*ssrc);
+            }
+            belegt
+        });
+        let ssrcs = self
+            .ssrcs
+            .iter()
+            .map(|eintrag| JitterMetrik {
+                ssrc: *eintrag.key(),
+                statistik: eintrag.value().lesen(),
+            })
+            .collect();
+        JitterMetrikSnapshot { ssrcs, entfernt }
+    }
+
+    /// Startet die periodische Abtastung; `melden` erhaelt jeden Snapshot
+    pub fn starten<F>(
+        self,
+        state: VoiceState,
+        intervall: Duration,
+        melden: F,
+    ) -> tokio::task::JoinHandle<()>
+    where
+        F: Fn(&JitterMetrikSnapshot) + Send + 'static,
+    {
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(intervall);
+            loop {
+                ticker.tick().await;
+                melden(&self.abtasten(&state));
+            }
+        })
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Socket-Abtastung
+// ---------------------------------------------------------------------------
+
+/// Abtastung der Kernel-Zaehler des Voice-Sockets
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SocketSnapshot {
+    /// Aktuelle Zaehlerstaende
+    pub zaehler: SocketZaehler,
+    /// Effektive Puffergroessen
+    pub puffer: SocketPuffer,
+    /// Seit der letzten Abtastung verworfene Datagramme
+    pub neue_drops: u64,
+}
+
+impl SocketSnapshot {
+    /// Belegung des Empfangspuffers (0.0–1.0)
+    pub fn puffer_auslastung(&self) -> f64 {
+        if self.puffer.empfang == 0 {
+            return 0.0;
+        }
+        (self.zaehler.empfangs_warteschlange as f64 / self.puffer.empfang as f64).min(1.0)
+    }
+}
+
+/// Tastet die Kernel-Zaehler des Voice-Sockets ab
+///
+/// Verwirft der Kernel Datagramme, weil die Empfangs-Loop nicht hinterherkommt,
+/// taucht das sonst nur als (vermeintlicher) Netzwerkverlust auf.
+pub struct SocketAbtaster {
+    server: Arc<VoiceServer>,
+    erkennung: DropErkennung,
+}
+
+impl SocketAbtaster {
+    pub fn neu(server: Arc<VoiceServer>) -> Self {
+        Self {
+            server,
+            erkennung: DropErkennung::new(),
+        }
+    }
+
+    /// Liest die Zaehler einmal und warnt bei neu verworfenen Datagrammen
+    ///
+    /// Gibt `None` zurueck, wenn die Plattform keine Zaehler bereitstellt.
+    pub fn abtasten(&mut self) -> Option<SocketSnapshot> {
+        let zaehler = self.server.socket_zaehler()?;
+        let snapshot = SocketSnapshot {
+            zaehler,
+            puffer: self.server.socket_puffer(),
+            neue_drops: self.erkennung.neue_drops(&zaehler),
+        };
+        if snapshot.neue_drops > 0 {
+            tracing::warn!(
+                verworfen = snapshot.neue_drops,
+                gesamt = zaehler.verworfen,
+                empfangspuffer = snapshot.puffer.empfang,
+                hinweis = ABHILFE_HINWEIS,
+                "Kernel hat Voice-Datagramme verworfen"
+            );
+        }
+        Some(snapshot)
+    }
+
+    /// Startet die periodische Abtastung; `melden` erhaelt jeden Snapshot
+    ///
+    /// Stellt die Plattform keine Zaehler bereit, endet der Task sofort.
+    pub fn starten<F>(mut self, intervall: Duration, melden: F) -> tokio::task::JoinHandle<()>
+    where
+        F: Fn(&SocketSnapshot) + Send + 'static,
+    {
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(intervall);
+            loop {
+                ticker.tick().await;
+                let Some(snapshot) = self.abtasten() else {
+                    tracing::debug!("Socket-Zaehler auf dieser Plattform nicht verfuegbar");
+                    return;
+                };
+                melden(&snapshot);
+            }
+        })
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Tests
 // ---------------------------------------------------------------------------
@@ -426,4 +661,64 @@ mod tests {
             .expect("Snapshot sollte via Broadcast ankommen");
         assert_eq!(snap.user_id, uid);
     }
+
+    #[test]
+    fn jitter_metriken_folgen_der_registrierung() {
+        let state = VoiceState::neu();
+        let sammler = VoiceMetricsCollector::neu();
+        state.client_registrieren(UserId::new(), 7, "127.0.0.1:5000".parse().unwrap());
+
+        let zaehler = sammler.zaehler(7);
+        zaehler.melden(&JitterBufferStatistik {
+            empfangen: 10,
+            verloren: 2,
+            duplikate: 1,
+            jitter_ticks: 480,
+            fuellstand: 3,
+            ..Default::default()
+        });
+        let snapshot = sammler.abtasten(&state);
+        assert!(snapshot.entfernt.is_empty());
+        assert_eq!(snapshot.ssrcs.len(), 1);
+        let statistik = &snapshot.ssrcs[0].statistik;
+        assert_eq!((statistik.empfangen, statistik.verloren), (10, 2));
+        assert_eq!((statistik.jitter_ticks, statistik.fuellstand), (480, 3));
+        assert!(VoiceMetricsCollector::ist_aktiv(&zaehler));
+
+        // Abgemeldete SSRC wird einmal als entfernt gemeldet
+        state.client_entfernen(&state.user_id_von_ssrc(7).unwrap());
+        assert_eq!(sammler.abtasten(&state).entfernt, vec![7]);
+        assert_eq!(sammler.ssrc_anzahl(), 0);
+        assert!(!VoiceMetricsCollector::ist_aktiv(&zaehler));
+        assert!(sammler.abtasten(&state).entfernt.is_empty());
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn socket_abtaster_erkennt_drops() {
+        use crate::udp::VoiceServerConfig;
+        use crate::ChannelRouter;
+
+        // Minimaler Empfangspuffer und keine Empfangs-Loop: der Kernel verwirft
+        let mut config = VoiceServerConfig::neu("127.0.0.1:0".parse().unwrap());
+        config.empfangspuffer = Some(1);
+        let server = VoiceServer::binden(config, ChannelRouter::neu(), VoiceState::neu())
+            .await
+            .unwrap();
+        let ziel = server.lokale_adresse().unwrap();
+        let mut abtaster = SocketAbtaster::neu(Arc::new(server));
+
+        let vorher = abtaster.abtasten().expect("Zaehler unter Linux verfuegbar");
+        assert_eq!(vorher.neue_drops, 0);
+
+        let sender = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+        for _ in 0..64 {
+            sender.send_to(&[0u8; 1000], ziel).unwrap();
+        }
+
+        let nachher = abtaster.abtasten().unwrap();
+        assert!(nachher.neue_drops > 0);
+        assert!(nachher.puffer_auslastung() > 0.0);
+        assert_eq!(abtaster.abtasten().unwrap().neue_drops, 0);
+    }
 }
@@ -0,0 +1,186 @@
+//! MOS-Schaetzung – Sprachqualitaet als Note von 1 bis 5
+//!
+//! Vereinfachtes E-Modell nach ITU-T G.107: aus Verzoegerung, Restverlust
+//! und Codec-Bitrate wird der Uebertragungsfaktor R (0–100) berechnet und in
+//! eine MOS-Note umgerechnet. Eine ungestoerte Verbindung erreicht ~4.4,
+//! ab ~3.6 werden Stoerungen hoerbar, unter 3 wird Sprache muehsam.
+//!
+//! ```text
+//! R   = R0 - Id(d) - Ie_eff
+//! Id  = 0.024·d + 0.11·(d - 177.3)   (zweiter Term erst ab d > 177.3 ms)
+//! Ie_eff = Ie + (95 - Ie) · Ppl / (Ppl + Bpl)
+//! MOS = 1 + 0.035·R + R·(R - 60)·(100 - R)·7e-6
+//! ```
+//!
+//! `Ppl` ist der Verlust in Prozent, der nach PLC/FEC noch hoerbar bleibt,
+//! zusammen mit den vom Jitter-Buffer verworfenen (zu spaeten) Paketen.
+//! `Ie` bestraft niedrige Opus-Bitraten.
+//!
+//! Alle Konstanten stehen hier; die Tests halten die Noten fuer bekannte
+//! Eingaben fest, damit Aenderungen an der Formel bewusst erfolgen. Die
+//! Schaetzung ist rein und kostet nur wenige Gleitkomma-Operationen.
+
+/// Grundwert von R ohne jede Beeintraechtigung (G.107 Standardwerte)
+pub const R0: f64 = 93.2;
+
+/// Verzoegerung (ms), ab der die Interaktivitaet spuerbar leidet
+pub const VERZOEGERUNG_KNICK_MS: f64 = 177.3;
+/// Abzug je ms Einweg-Verzoegerung
+pub const VERZOEGERUNG_FAKTOR: f64 = 0.024;
+/// Zusaetzlicher Abzug je ms oberhalb von [`VERZOEGERUNG_KNICK_MS`]
+pub const VERZOEGERUNG_FAKTOR_UEBER_KNICK: f64 = 0.11;
+
+/// Verlust-Robustheit (Bpl) von Opus mit PLC
+pub const VERLUST_ROBUSTHEIT: f64 = 14.0;
+/// Obergrenze der Beeintraechtigung durch Verlust
+pub const VERLUST_MAX: f64 = 95.0;
+
+/// Bitrate (kbps), ab der Opus-Sprache als verlustfrei gilt
+pub const VOLLE_BITRATE_KBPS: f64 = 32.0;
+/// Abzug je Halbierung der Bitrate unter [`VOLLE_BITRATE_KBPS`] (mal ln 2)
+pub const BITRATE_FAKTOR: f64 = 12.0;
+
+/// Feste Verzoegerung durch Codec, Framing und Audiopuffer (ms)
+pub const CODEC_VERZOEGERUNG_MS: f64 = 26.5;
+
+/// Beste erreichbare Note
+pub const MOS_MAX: f64 = 4.5;
+/// Schlechteste Note
+pub const MOS_MIN: f64 = 1.0;
+
+/// Eingaben der Schaetzung
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MosEingabe {
+    /// Geschaetzte Einweg-Verzoegerung Mund-zu-Ohr (ms)
+    pub einweg_verzoegerung_ms: f64,
+    /// Verlustrate nach PLC/FEC (0.0–1.0)
+    pub verlust_rate: f64,
+    /// Anteil vom Jitter-Buffer verworfener Pakete (0.0–1.0)
+    pub verwurf_rate: f64,
+    /// Codec-Bitrate (kbps)
+    pub bitrate_kbps: f64,
+}
+
+impl MosEingabe {
+    /// Uebertragungsfaktor R (0–100)
+    pub fn r_faktor(&self) -> f64 {
+        let r = R0 - verzoegerung_abzug(self.einweg_verzoegerung_ms) - self.verlust_abzug();
+        r.clamp(0.0, 100.0)
+    }
+
+    /// Geschaetzte MOS-Note (1.0–4.5)
+    pub fn mos(&self) -> f64 {
+        mos_aus_r(self.r_faktor())
+    }
+
+    /// Ie_eff: Codec-Abzug, durch Verlust und Verwurf verstaerkt
+    fn verlust_abzug(&self) -> f64 {
+        let ie = bitrate_abzug(self.bitrate_kbps);
+        let verlust = self.verlust_rate.clamp(0.0, 1.0);
+        let verwurf = self.verwurf_rate.clamp(0.0, 1.0);
+        // Verworfene Pakete fehlen genauso wie verlorene
+        let ppl = (1.0 - (1.0 - verlust) * (1.0 - verwurf)) * 100.0;
+        ie + (VERLUST_MAX - ie) * ppl / (ppl + VERLUST_ROBUSTHEIT)
+    }
+}
+
+/// Id: Abzug fuer Einweg-Verzoegerung
+fn verzoegerung_abzug(verzoegerung_ms: f64) -> f64 {
+    let d = verzoegerung_ms.max(0.0);
+    let ueber_knick = (d - VERZOEGERUNG_KNICK_MS).max(0.0);
+    VERZOEGERUNG_FAKTOR * d + VERZOEGERUNG_FAKTOR_UEBER_KNICK * ueber_knick
+}
+
+/// Ie: Abzug fuer niedrige Bitrate
+fn bitrate_abzug(bitrate_kbps: f64) -> f64 {
+    if bitrate_kbps >= VOLLE_BITRATE_KBPS {
+        return 0.0;
+    }
+    // Unter 1 kbps gibt es keine Sprache mehr; Division durch 0 vermeiden
+    BITRATE_FAKTOR * (VOLLE_BITRATE_KBPS / bitrate_kbps.max(1.0)).ln()
+}
+
+/// Rechnet R in eine MOS-Note um
+pub fn mos_aus_r(r: f64) -> f64 {
+    if r <= 0.0 {
+        return MOS_MIN;
+    }
+    let mos = 1.0 + 0.035 * r + r * (r - 60.0) * (100.0 - r) * 7e-6;
+    mos.clamp(MOS_MIN, MOS_MAX)
+}
+
+/// Schaetzt die Einweg-Verzoegerung aus RTT und Jitter-Buffer-Tiefe
+///
+/// Die Netzlaufzeit ist die halbe RTT; dazu kommen Puffer und Codec.
+pub fn einweg_verzoegerung_ms(rtt_ms: u32, puffer_ms: u32) -> f64 {
+    f64::from(rtt_ms) / 2.0 + f64::from(puffer_ms) + CODEC_VERZOEGERUNG_MS
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn eingabe(verzoegerung: f64, verlust: f64, verwurf: f64, bitrate: f64) -> MosEingabe {
+        MosEingabe {
+            einweg_verzoegerung_ms: verzoegerung,
+            verlust_rate: verlust,
+            verwurf_rate: verwurf,
+            bitrate_kbps: bitrate,
+        }
+    }
+
+    /// Bekannte Eingaben und ihre Noten (auf 0.01 genau)
+    #[test]
+    fn noten_tabelle() {
+        let tabelle = [
+            // Verzoegerung, Verlust, Verwurf, Bitrate -> MOS
+            (20.0, 0.0, 0.0, 64.0, 4.40),
+            (CODEC_VERZOEGERUNG_MS, 0.0, 0.0, 32.0, 4.40),
+            (150.0, 0.0, 0.0, 64.0, 4.33),
+            (150.0, 0.05, 0.0, 64.0, 3.33),
+            (150.0, 0.0, 0.05, 64.0, 3.33),
+            (50.0, 0.01, 0.0, 64.0, 4.22),
+            (300.0, 0.0, 0.0, 64.0, 3.71),
+            (20.0, 0.0, 0.0, 16.0, 4.18),
+            (20.0, 0.0, 0.0, 8.0, 3.87),
+            (400.0, 0.2, 0.05, 8.0, 1.0),
+        ];
+        for (verzoegerung, verlust, verwurf, bitrate, erwartet) in tabelle {
+            let mos = eingabe(verzoegerung, verlust, verwurf, bitrate).mos();
+            assert!(
+                (mos - erwartet).abs() < 0.005,
+                "d={verzoegerung} verlust={verlust} verwurf={verwurf} \
+                 bitrate={bitrate}: {mos:.3} statt {erwartet}"
+            );
+        }
+    }
+
+    #[test]
+    fn note_faellt_mit_jeder_beeintraechtigung() {
+        let gut = eingabe(40.0, 0.0, 0.0, 48.0).mos();
+        assert!(eingabe(120.0, 0.0, 0.0, 48.0).mos() < gut);
+        assert!(eingabe(40.0, 0.02, 0.0, 48.0).mos() < gut);
+        assert!(eingabe(40.0, 0.0, 0.02, 48.0).mos() < gut);
+        assert!(eingabe(40.0, 0.0, 0.0, 12.0).mos() < gut);
+    }
+
+    #[test]
+    fn unsinnige_eingaben_bleiben_im_wertebereich() {
+        for e in [
+            eingabe(-10.0, -1.0, 2.0, 0.0),
+            eingabe(f64::MAX, 0.0, 0.0, 64.0),
+            eingabe(0.0, 0.0, 0.0, f64::MAX),
+        ] {
+            let mos = e.mos();
+            assert!((MOS_MIN..=MOS_MAX).contains(&mos), "{e:?}: {mos}");
+        }
+    }
+
+    #[test]
+    fn einweg_verzoegerung_aus_rtt_und_puffer() {
+        assert_eq!(
+            einweg_verzoegerung_ms(100, 40),
+            50.0 + 40.0 + CODEC_VERZOEGERUNG_MS
+        );
+    }
+}
@@ -8,14 +8,33 @@
 //! ```text
 //! UDP Socket (recv_from)
 //!     |
+//!     +--> PingPaket::ist_ping() --> PingReflektor --> send_to (Antwort)
+//!     |                              (ohne Session, per IP gedrosselt)
+//!     +--> HelloPaket::ist_hello() --> VoiceState::hello_pruefen()
+//!     |                              (SSRC + HMAC; merkt sich die Absender-
+//!     |                               adresse, Bestaetigung per send_to)
 //!     v
 //! VoicePacket::decode()      <- Validierung
 //!     |
 //!     v
-//! VoiceState::user_id_von_endpunkt()  <- Client identifizieren
+//! VoiceState::absender_von_endpunkt() <- Client identifizieren (nur per
+//!     |                                  Hello bestaetigte Endpunkte)
+//!     v
+//! VoiceState::sendeverbot_verbuchen() <- Nur-Zuhoerer verwerfen
+//!     |
+//!     v
+//! ChannelRouter::notfall_unterdrueckt() <- Notfall-stumme Kanaele
+//!     |
+//!     v
+//! JitterMessung::paket()              <- Jitter-Metriken pro SSRC (optional)
+//!     |
+//!     v
+//! VoiceState::frische_pruefen()       <- Verspaetete Pakete verwerfen (optional)
 //!     |
 //!     v
 //! ChannelRouter::paket_weiterleiten() <- An alle anderen Teilnehmer
+//!     |                                  (verworfene Pakete vorher per
+//!     |                                   paket_unterdrueckt() verbucht)
 //!     |
 //!     +--> Empfaenger-Send-Queue (mpsc) --> UDP send_to Task
 //! ```
@@ -24,19 +43,44 @@
 //! - Minimale Allocations: Recv-Buffer wird wiederverwendet (stack-allocated)
 //! - Zero-copy Weiterleitung via Arc<Vec<u8>>
 //! - Separater Sende-Task pro Client (verhindert Head-of-Line-Blocking)
+//!
+//! ## Socket-Fehler
+//! ICMP-Rueckmeldungen ("Port unreachable", unter Windows `WSAECONNRESET`
+//! beim naechsten `recv_from`) betreffen nur ein Ziel und beenden die
+//! Empfangsschleife nie; sie werden gezaehlt. Meldet ein Ziel beim Senden
+//! wiederholt Unerreichbarkeit, pausiert sein Sende-Task kurz und verwirft
+//! solange Pakete statt den Socket zu belasten.
 
+use crate::aktivitaet::AktivitaetsTracker;
+use crate::frische::Frische;
+use crate::jitter_buffer::AdaptiveJitterBuffer;
+use crate::ping::{PingLimits, PingReflektor};
 use crate::router::ChannelRouter;
-use crate::state::VoiceState;
+use crate::state::{HelloErgebnis, VoiceState};
+use crate::telemetry::{JitterZaehler, VoiceMetricsCollector};
 use speakeasy_core::types::{ChannelId, UserId};
-use speakeasy_protocol::voice::VoicePacket;
+use speakeasy_protocol::qos::{self, QosStatus, SockRef};
+use speakeasy_protocol::socket_statistik::{self, SocketPuffer, SocketZaehler};
+use speakeasy_protocol::udp_fehler::{self, UdpFehlerArt};
+use speakeasy_protocol::voice::{HelloPaket, PingPaket, VoicePacket, VoicePacketHeader};
+use std::collections::HashMap;
 use std::net::SocketAddr;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::net::UdpSocket;
 use tokio::sync::mpsc;
+use tokio::time::Instant;
 
 /// Maximale UDP-Paketgroesse (Header 16 + Max-Payload 1280 + Puffer)
 const UDP_BUFFER_SIZE: usize = 1400;
 
+/// Pause eines Sende-Tasks, nachdem sein Ziel als unerreichbar gemeldet wurde
+pub const ZIEL_PAUSE: Duration = Duration::from_millis(250);
+
+/// Abstand der Herzschlaege der Empfangs-Loop, auch ohne Verkehr
+pub const HERZSCHLAG_INTERVALL: Duration = Duration::from_secs(1);
+
 // ---------------------------------------------------------------------------
 // VoiceServer-Konfiguration
 // ---------------------------------------------------------------------------
@@ -48,6 +92,17 @@ pub struct VoiceServerConfig {
     pub bind_addr: SocketAddr,
     /// Groesse des Sende-Kanalspuffers pro Client
     pub send_queue_groesse: usize,
+    /// DSCP-Markierung fuer ausgehende Voice-Pakete (`None` = keine Markierung)
+    pub dscp: Option<u8>,
+    /// Empfangspuffer des Sockets in Bytes (`None` = Systemstandard)
+    pub empfangspuffer: Option<usize>,
+    /// Sendepuffer des Sockets in Bytes (`None` = Systemstandard)
+    pub sendepuffer: Option<usize>,
+    /// Maximale Verspaetung gegenueber dem Takt des Absenders, danach wird
+    /// ein Paket nicht mehr weitergeleitet (`None` = alles weiterleiten)
+    pub paket_ttl: Option<Duration>,
+    /// Grenzen fuer die Beantwortung von Pings (`None` = Pings ignorieren)
+    pub ping: Option<PingLimits>,
 }
 
 impl VoiceServerConfig {
@@ -56,6 +111,11 @@ impl VoiceServerConfig {
         Self {
             bind_addr,
             send_queue_groesse: 128,
+            dscp: Some(qos::DSCP_EF),
+            empfangspuffer: None,
+            sendepuffer: None,
+            paket_ttl: None,
+            ping: Some(PingLimits::default()),
         }
     }
 }
@@ -64,12 +124,39 @@ impl VoiceServerConfig {
 // ClientSender – Sende-Task pro Client
 // ---------------------------------------------------------------------------
 
+/// Sendepause fuer ein als unerreichbar gemeldetes Ziel
+#[derive(Debug, Clone, Default)]
+pub struct ZielPause {
+    bis: Option<Instant>,
+}
+
+impl ZielPause {
+    /// Vermerkt eine Unerreichbarkeits-Meldung: Pause bis `jetzt + ZIEL_PAUSE`
+    pub fn melden(&mut self, jetzt: Instant) {
+        self.bis = Some(jetzt + ZIEL_PAUSE);
+    }
+
+    /// Gibt `true` zurueck solange die Pause laeuft
+    pub fn aktiv(&mut self, jetzt: Instant) -> bool {
+        match self.bis {
+            Some(bis) if jetzt < bis => true,
+            Some(_) => {
+                self.bis = None;
+                false
+            }
+            None => false,
+        }
+    }
+}
+
 /// Handle fuer den Sende-Task eines Clients
 ///
 /// Wenn dieses Handle gedroppt wird, wird der Sende-Task beendet.
 pub struct ClientSenderHandle {
     /// Sende-Queue: Pakete hier einlegen -> werden via UDP versendet
     pub tx: mpsc::Sender<Arc<Vec<u8>>>,
+    /// Unerreichbarkeits-Meldungen des Ziels beim Senden
+    unerreichbar: Arc<AtomicU64>,
     /// Task-Handle (Abbruch beim Drop)
     _task: tokio::task::JoinHandle<()>,
 }
@@ -80,9 +167,15 @@ impl ClientSenderHandle {
     /// Liest aus der mpsc-Queue und sendet via UDP an `ziel_addr`.
     pub fn starten(socket: Arc<UdpSocket>, ziel_addr: SocketAddr, queue_groesse: usize) -> Self {
         let (tx, mut rx) = mpsc::channel::<Arc<Vec<u8>>>(queue_groesse);
+        let unerreichbar = Arc::new(AtomicU64::new(0));
+        let zaehler = Arc::clone(&unerreichbar);
 
         let task = tokio::spawn(async move {
+            let mut pause = ZielPause::default();
             while let Some(daten) = rx.recv().await {
+                if pause.aktiv(Instant::now()) {
+                    continue;
+                }
                 match socket.send_to(&daten, ziel_addr).await {
                     Ok(_) => {
                         tracing::trace!(
@@ -91,6 +184,15 @@ impl ClientSenderHandle {
                             "UDP-Paket gesendet"
                         );
                     }
+                    Err(e) if udp_fehler::einordnen(&e) == UdpFehlerArt::ZielUnerreichbar => {
+                        zaehler.fetch_add(1, Ordering::Relaxed);
+                        pause.melden(Instant::now());
+                        tracing::debug!(
+                            fehler = %e,
+                            ziel = %ziel_addr,
+                            "Ziel unerreichbar, Sendepause"
+                        );
+                    }
                     Err(e) => {
                         tracing::warn!(
                             fehler = %e,
@@ -103,7 +205,80 @@ impl ClientSenderHandle {
             tracing::debug!(ziel = %ziel_addr, "Sende-Task beendet");
         });
 
-        Self { tx, _task: task }
+        Self {
+            tx,
+            unerreichbar,
+            _task: task,
+        }
+    }
+
+    /// Anzahl der Unerreichbarkeits-Meldungen des Ziels seit dem Start
+    pub fn unerreichbar_gemeldet(&self) -> u64 {
+        self.unerreichbar.load(Ordering::Relaxed)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// JitterMessung – Jitter Buffer pro Absender
+// ---------------------------------------------------------------------------
+
+/// Nachgebildeter Jitter Buffer einer SSRC mit ihren exportierten Zaehlern
+struct JitterMesspunkt {
+    puffer: AdaptiveJitterBuffer,
+    zaehler: Arc<JitterZaehler>,
+}
+
+/// Jitter-Messung der Empfangsschleife
+///
+/// Gehoert allein dem Empfangs-Task und braucht deshalb keine Locks. Jeder
+/// Absender bekommt einen Jitter Buffer, der die Wiedergabe beim Empfaenger
+/// nachbildet (nur Header, ohne Nutzdaten); dessen Statistik landet per
+/// Atomics im [`VoiceMetricsCollector`].
+#[derive(Default)]
+struct JitterMessung {
+    messpunkte: HashMap<u32, JitterMesspunkt>,
+}
+
+impl JitterMessung {
+    fn paket(&mut self, sammler: &VoiceMetricsCollector, header: &VoicePacketHeader) {
+        let ssrc = header.ssrc;
+        let aktiv = self
+            .messpunkte
+            .get(&ssrc)
+            .is_some_and(|m| VoiceMetricsCollector::ist_aktiv(&m.zaehler));
+        if !aktiv {
+            // Vom Sammler entfernte SSRCs freigeben (selten: neue SSRC)
+            self.messpunkte
+                .retain(|_, m| VoiceMetricsCollector::ist_aktiv(&m.zaehler));
+        }
+        let messpunkt = self
+            .messpunkte
+            .entry(ssrc)
+            .or_insert_with(|| JitterMesspunkt {
+                puffer: AdaptiveJitterBuffer::standard(),
+                zaehler: sammler.zaehler(ssrc),
+            });
+
+        messpunkt.puffer.push(VoicePacket {
+            header: *header,
+            payload: Vec::new(),
+        });
+        // Wiedergabe nachbilden: alles ueber der Zielgroesse gilt als abgespielt
+        while messpunkt.puffer.fuellstand() > messpunkt.puffer.ziel_groesse()
+            && messpunkt.puffer.pop().is_some()
+        {}
+        messpunkt.zaehler.melden(messpunkt.puffer.statistik());
+    }
+}
+
+/// Datagramm-Quelle der Empfangsschleife (in Tests austauschbar)
+trait DatagrammQuelle {
+    async fn empfangen(&self, buf: &mut [u8]) -> std::io::Result<(usize, SocketAddr)>;
+}
+
+impl DatagrammQuelle for UdpSocket {
+    async fn empfangen(&self, buf: &mut [u8]) -> std::io::Result<(usize, SocketAddr)> {
+        self.recv_from(buf).await
     }
 }
 
@@ -120,6 +295,24 @@ pub struct VoiceServer {
     socket: Arc<UdpSocket>,
     router: ChannelRouter,
     state: VoiceState,
+    qos: QosStatus,
+    puffer: SocketPuffer,
+    aktivitaet: Option<AktivitaetsTracker>,
+    metriken: Option<VoiceMetricsCollector>,
+    herzschlag: Option<Arc<AtomicU64>>,
+    ping: Option<PingReflektor>,
+    /// Wegen Verspaetung verworfene Pakete (alle Absender)
+    veraltet: AtomicU64,
+    /// Von Nur-Zuhoerern gesendete, verworfene Pakete (alle Absender)
+    nur_hoeren: AtomicU64,
+    /// Wegen Notfall-Stummschaltung verworfene Pakete (alle Absender)
+    notfall: AtomicU64,
+    /// ICMP-Rueckmeldungen beim Empfang (Ziel unbekannt)
+    empfang_unerreichbar: AtomicU64,
+    /// Pakete von unbekannten SSRCs oder unbestaetigten Endpunkten
+    unbekannt: AtomicU64,
+    /// Hellos mit ungueltigem HMAC
+    hello_abgelehnt: AtomicU64,
 }
 
 impl VoiceServer {
@@ -132,11 +325,53 @@ impl VoiceServer {
         let socket = UdpSocket::bind(config.bind_addr).await?;
         tracing::info!(addr = %config.bind_addr, "UDP Voice Server gebunden");
 
+        let qos = qos::markieren(SockRef::from(&socket), config.dscp);
+        if let QosStatus::Abgelehnt { dscp, grund } = &qos {
+            tracing::warn!(dscp, grund = %grund, "DSCP-Markierung vom System abgelehnt");
+        }
+
+        // Das System darf die Werte anpassen, deshalb den effektiven Wert loggen
+        let puffer = socket_statistik::puffer_setzen(
+            SockRef::from(&socket),
+            config.empfangspuffer,
+            config.sendepuffer,
+        )?;
+        tracing::info!(
+            empfangspuffer = puffer.empfang,
+            sendepuffer = puffer.senden,
+            "Voice-Socket-Puffer"
+        );
+        match udp_fehler::connreset_abschalten(SockRef::from(&socket)) {
+            Ok(true) => tracing::debug!("ICMP-Rueckmeldungen an recv_from abgeschaltet"),
+            Ok(false) => {}
+            Err(e) => tracing::warn!(fehler = %e, "SIO_UDP_CONNRESET nicht gesetzt"),
+        }
+
+        if config.empfangspuffer.is_some_and(|g| puffer.empfang < g) {
+            tracing::warn!(
+                angefordert = config.empfangspuffer,
+                effektiv = puffer.empfang,
+                "Empfangspuffer vom System begrenzt (unter Linux net.core.rmem_max pruefen)"
+            );
+        }
+
         Ok(Self {
+            ping: config.ping.map(PingReflektor::neu),
             config,
             socket: Arc::new(socket),
             router,
             state,
+            qos,
+            puffer,
+            aktivitaet: None,
+            metriken: None,
+            herzschlag: None,
+            veraltet: AtomicU64::new(0),
+            nur_hoeren: AtomicU64::new(0),
+            notfall: AtomicU64::new(0),
+            empfang_unerreichbar: AtomicU64::new(0),
+            unbekannt: AtomicU64::new(0),
+            hello_abgelehnt: AtomicU64::new(0),
         })
     }
 
@@ -145,6 +380,77 @@ impl VoiceServer {
         self.socket.local_addr()
     }
 
+    /// Meldet Sprachaktivitaet (Pakete mit Speaking-Status) an den Tracker
+    pub fn aktivitaet_verfolgen(&mut self, tracker: AktivitaetsTracker) {
+        self.aktivitaet = Some(tracker);
+    }
+
+    /// Meldet die Jitter-Buffer-Statistik jedes Absenders an den Sammler
+    pub fn metriken_erfassen(&mut self, sammler: VoiceMetricsCollector) {
+        self.metriken = Some(sammler);
+    }
+
+    /// Schreibt bei jedem Durchlauf der Empfangs-Loop die Unix-Zeit in ms
+    ///
+    /// Ohne Verkehr schlaegt die Loop alle [`HERZSCHLAG_INTERVALL`]; bleibt
+    /// der Wert stehen, haengt die Loop oder ist beendet.
+    pub fn herzschlag_melden(&mut self, herzschlag: Arc<AtomicU64>) {
+        self.herzschlag = Some(herzschlag);
+    }
+
+    /// Gibt zurueck ob ausgehende Voice-Pakete DSCP-markiert werden
+    pub fn qos_status(&self) -> &QosStatus {
+        &self.qos
+    }
+
+    /// Gibt die effektiven Puffergroessen des Sockets zurueck
+    pub fn socket_puffer(&self) -> SocketPuffer {
+        self.puffer
+    }
+
+    /// Liest die Kernel-Zaehler des Sockets (`None` wenn nicht verfuegbar)
+    pub fn socket_zaehler(&self) -> Option<SocketZaehler> {
+        socket_statistik::zaehler_lesen(SockRef::from(&*self.socket))
+    }
+
+    /// Anzahl der wegen Verspaetung verworfenen Pakete seit dem Start
+    pub fn veraltet_verworfen(&self) -> u64 {
+        self.veraltet.load(Ordering::Relaxed)
+    }
+
+    /// Anzahl der verworfenen Pakete von Nur-Zuhoerern seit dem Start
+    pub fn nur_hoeren_verworfen(&self) -> u64 {
+        self.nur_hoeren.load(Ordering::Relaxed)
+    }
+
+    /// Anzahl der wegen Notfall-Stummschaltung verworfenen Pakete seit dem Start
+    pub fn notfall_verworfen(&self) -> u64 {
+        self.notfall.load(Ordering::Relaxed)
+    }
+
+    /// Anzahl der beim Empfang ignorierten ICMP-Rueckmeldungen seit dem Start
+    pub fn empfang_unerreichbar(&self) -> u64 {
+        self.empfang_unerreichbar.load(Ordering::Relaxed)
+    }
+
+    /// Anzahl der verworfenen Pakete unbekannter Absender seit dem Start
+    ///
+    /// Zaehlt Hellos mit unbekannter SSRC und Voice-Pakete von Endpunkten,
+    /// die kein Hello bestaetigt hat.
+    pub fn unbekannt_verworfen(&self) -> u64 {
+        self.unbekannt.load(Ordering::Relaxed)
+    }
+
+    /// Anzahl der abgelehnten Hellos (ungueltiger HMAC) seit dem Start
+    pub fn hello_abgelehnt(&self) -> u64 {
+        self.hello_abgelehnt.load(Ordering::Relaxed)
+    }
+
+    /// Gibt den Ping-Reflektor zurueck (`None` wenn Pings ignoriert werden)
+    pub fn ping_reflektor(&self) -> Option<&PingReflektor> {
+        self.ping.as_ref()
+    }
+
     /// Registriert einen Client und startet seinen Sende-Task
     ///
     /// Der Client kann danach Pakete empfangen und senden.
@@ -181,29 +487,50 @@ impl VoiceServer {
 
     /// Startet die Empfangs-Loop (laeuft bis `shutdown_rx` ein Signal sendet)
     ///
-    /// Diese Methode blockiert bis zum Shutdown-Signal.
-    pub async fn empfangs_loop_starten(&self, mut shutdown_rx: tokio::sync::oneshot::Receiver<()>) {
+    /// Diese Methode blockiert bis zum Shutdown-Signal. Socket-Fehler beenden
+    /// die Loop nie.
+    pub async fn empfangs_loop_starten(&self, shutdown_rx: tokio::sync::oneshot::Receiver<()>) {
+        self.empfangs_loop(&*self.socket, shutdown_rx).await;
+    }
+
+    async fn empfangs_loop<Q: DatagrammQuelle>(
+        &self,
+        quelle: &Q,
+        mut shutdown_rx: tokio::sync::oneshot::Receiver<()>,
+    ) {
         // Stack-allokierter Empfangspuffer – wird wiederverwendet (kein Heap pro Paket)
         let mut buf = [0u8; UDP_BUFFER_SIZE];
+        let mut messung = JitterMessung::default();
+        let mut takt = tokio::time::interval(HERZSCHLAG_INTERVALL);
+        takt.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
 
         tracing::info!("Voice-Empfangs-Loop gestartet");
 
         loop {
+            if let Some(herzschlag) = &self.herzschlag {
+                let jetzt = std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .map(|d| d.as_millis() as u64)
+                    .unwrap_or(0);
+                herzschlag.store(jetzt, Ordering::Relaxed);
+            }
+
             tokio::select! {
                 // Eingehendes UDP-Paket
-                result = self.socket.recv_from(&mut buf) => {
+                result = quelle.empfangen(&mut buf) => {
                     match result {
                         Ok((len, absender_addr)) => {
-                            self.paket_verarbeiten(&buf[..len], absender_addr).await;
-                        }
-                        Err(e) => {
-                            tracing::error!(fehler = %e, "UDP-Empfangsfehler");
-                            // Kurze Pause um Busy-Loop bei persistentem Fehler zu vermeiden
-                            tokio::time::sleep(std::time::Duration::from_millis(1)).await;
+                            self
+                                .paket_verarbeiten(&buf[..len], absender_addr, &mut messung)
+                                .await;
                         }
+                        Err(e) => self.empfangsfehler_behandeln(&e).await,
                     }
                 }
 
+                // Herzschlag auch ohne Verkehr
+                _ = takt.tick() => {}
+
                 // Shutdown-Signal
                 _ = &mut shutdown_rx => {
                     tracing::info!("Voice-Server: Shutdown-Signal empfangen");
@@ -219,10 +546,107 @@ impl VoiceServer {
     // Internes Paket-Processing
     // -----------------------------------------------------------------------
 
+    /// Behandelt einen Fehler von `recv_from`, ohne die Loop zu verlassen
+    async fn empfangsfehler_behandeln(&self, fehler: &std::io::Error) {
+        match udp_fehler::einordnen(fehler) {
+            UdpFehlerArt::ZielUnerreichbar => {
+                // Windows nennt hier nicht das betroffene Ziel: nur zaehlen
+                let gesamt = self.empfang_unerreichbar.fetch_add(1, Ordering::Relaxed) + 1;
+                tracing::trace!(fehler = %fehler, gesamt, "ICMP-Rueckmeldung beim Empfang ignoriert");
+            }
+            UdpFehlerArt::Voruebergehend => {}
+            UdpFehlerArt::Sonstiger => {
+                tracing::error!(fehler = %fehler, "UDP-Empfangsfehler");
+                // Kurze Pause um Busy-Loop bei persistentem Fehler zu vermeiden
+                tokio::time::sleep(std::time::Duration::from_millis(1)).await;
+            }
+        }
+    }
+
+    /// Beantwortet einen Ping direkt ueber den Socket
+    ///
+    /// Ohne Sende-Queue: ist der Socket voll, geht die Antwort verloren und
+    /// der Client verbucht die Probe als Verlust.
+    fn ping_beantworten(&self, daten: &[u8], absender_addr: SocketAddr) {
+        let Some(reflektor) = &self.ping else {
+            return;
+        };
+        let Some(antwort) = reflektor.verarbeiten(daten, absender_addr.ip()) else {
+            tracing::trace!(absender = %absender_addr, "Ping verworfen");
+            return;
+        };
+        if let Err(e) = self.socket.try_send_to(&antwort, absender_addr) {
+            tracing::trace!(absender = %absender_addr, fehler = %e, "Ping-Antwort nicht gesendet");
+        }
+    }
+
+    /// Prueft ein Hello und bestaetigt es dem Absender
+    ///
+    /// Die Bestaetigung ist so gross wie das Hello und geht ohne Sende-Queue
+    /// direkt ueber den Socket; geht sie verloren, wiederholt der Client.
+    fn hello_verarbeiten(&self, daten: &[u8], absender_addr: SocketAddr) {
+        let hello = match HelloPaket::decode(daten) {
+            Ok(h) if !h.antwort => h,
+            _ => {
+                self.hello_abgelehnt.fetch_add(1, Ordering::Relaxed);
+                tracing::debug!(absender = %absender_addr, "Ungueltiges Hello verworfen");
+                return;
+            }
+        };
+        match self.state.hello_pruefen(&hello, absender_addr) {
+            HelloErgebnis::Bestaetigt(_) => {
+                let antwort = hello.bestaetigen().encode();
+                if let Err(e) = self.socket.try_send_to(&antwort, absender_addr) {
+                    tracing::trace!(absender = %absender_addr, fehler = %e, "Hello-Bestaetigung nicht gesendet");
+                }
+            }
+            HelloErgebnis::UnbekannteSsrc => {
+                self.unbekannt.fetch_add(1, Ordering::Relaxed);
+                tracing::debug!(
+                    absender = %absender_addr,
+                    ssrc = hello.ssrc,
+                    "Hello mit unbekannter SSRC verworfen"
+                );
+            }
+            HelloErgebnis::Ungueltig => {
+                self.hello_abgelehnt.fetch_add(1, Ordering::Relaxed);
+                tracing::debug!(
+                    absender = %absender_addr,
+                    ssrc = hello.ssrc,
+                    "Hello mit ungueltigem HMAC verworfen"
+                );
+            }
+            HelloErgebnis::Wiederholt | HelloErgebnis::EndpunktGebunden => {
+                self.hello_abgelehnt.fetch_add(1, Ordering::Relaxed);
+                tracing::debug!(
+                    absender = %absender_addr,
+                    ssrc = hello.ssrc,
+                    versuch = hello.versuch,
+                    "Wiederholtes oder fremdes Hello verworfen"
+                );
+            }
+        }
+    }
+
     /// Verarbeitet ein eingehendes UDP-Paket
     ///
     /// Hot Path: Minimale Allocations, schneller Pfad bei Fehler (early return).
-    async fn paket_verarbeiten(&self, daten: &[u8], absender_addr: SocketAddr) {
+    async fn paket_verarbeiten(
+        &self,
+        daten: &[u8],
+        absender_addr: SocketAddr,
+        messung: &mut JitterMessung,
+    ) {
+        // Pings beantworten, bevor irgendein Session-Zustand angefasst wird
+        if PingPaket::ist_ping(daten) {
+            self.ping_beantworten(daten, absender_addr);
+            return;
+        }
+        if HelloPaket::ist_hello(daten) {
+            self.hello_verarbeiten(daten, absender_addr);
+            return;
+        }
+
         // Paket dekodieren und validieren
         let paket = match VoicePacket::decode(daten) {
             Ok(p) => p,
@@ -237,9 +661,10 @@ impl VoiceServer {
         };
 
         // Client anhand des Endpunkts identifizieren
-        let user_id = match self.state.user_id_von_endpunkt(&absender_addr) {
-            Some(uid) => uid,
+        let (user_id, sprecher) = match self.state.absender_von_endpunkt(&absender_addr) {
+            Some(absender) => absender,
             None => {
+                self.unbekannt.fetch_add(1, Ordering::Relaxed);
                 tracing::debug!(
                     absender = %absender_addr,
                     ssrc = paket.header.ssrc,
@@ -249,15 +674,69 @@ impl VoiceServer {
             }
         };
 
+        // Nur-Zuhoerer senden nie: weder weiterleiten noch als Sprechen werten
+        if self.state.sendeverbot_verbuchen(&user_id) {
+            self.nur_hoeren.fetch_add(1, Ordering::Relaxed);
+            self.router.paket_unterdrueckt(&paket.header, &user_id);
+            tracing::trace!(
+                user_id = %user_id,
+                sequence = paket.header.sequence,
+                "Paket eines Nur-Zuhoerers verworfen"
+            );
+            return;
+        }
+
+        // Notfall-stumm geschalteter Kanal: auch nicht als Sprechen werten
+        if self.router.notfall_unterdrueckt(&user_id) {
+            self.notfall.fetch_add(1, Ordering::Relaxed);
+            self.router.paket_unterdrueckt(&paket.header, &user_id);
+            tracing::trace!(
+                user_id = %user_id,
+                sequence = paket.header.sequence,
+                "Paket verworfen (Notfall-Stummschaltung)"
+            );
+            return;
+        }
+
         // Speaking-Status aus Flags aktualisieren
         if paket.spricht_start() {
             self.state.speaking_setzen(&user_id, true);
         } else if paket.spricht_stop() {
             self.state.speaking_setzen(&user_id, false);
         }
+        sprecher.paket(paket.spricht_stop());
+        if let Some(tracker) = &self.aktivitaet {
+            let spricht = paket.spricht_start()
+                || self.state.client_state(&user_id).is_some_and(|c| c.spricht);
+            if spricht {
+                tracker.melden(user_id);
+            }
+        }
 
-        // Paket-Zeitstempel aktualisieren
-        self.state.paket_zeitstempel_aktualisieren(&user_id);
+        // Paket-Zeitstempel und Uplink-Statistik aktualisieren
+        self.state
+            .uplink_paket_verbuchen(&user_id, paket.header.sequence);
+        if let Some(sammler) = &self.metriken {
+            messung.paket(sammler, &paket.header);
+        }
+
+        // Verspaetete Pakete verwirft der Jitter-Buffer der Empfaenger ohnehin.
+        // Sie zaehlen bereits als empfangen, also nicht als Uplink-Verlust.
+        if let Some(ttl) = self.config.paket_ttl {
+            if let Frische::Veraltet { verspaetung } =
+                self.state.frische_pruefen(&user_id, &paket.header, ttl)
+            {
+                self.veraltet.fetch_add(1, Ordering::Relaxed);
+                self.router.paket_unterdrueckt(&paket.header, &user_id);
+                tracing::trace!(
+                    user_id = %user_id,
+                    sequence = paket.header.sequence,
+                    verspaetung_ms = verspaetung.as_millis() as u64,
+                    "Veraltetes Voice-Paket verworfen"
+                );
+                return;
+            }
+        }
 
         // Paket an alle anderen Teilnehmer im Kanal weiterleiten
         let weitergeleitet = self.router.paket_weiterleiten(&paket, &user_id);
@@ -281,6 +760,7 @@ impl VoiceServer {
 mod tests {
     use super::*;
     use speakeasy_protocol::voice::VoicePacketHeader;
+    use std::collections::VecDeque;
     use std::net::{IpAddr, Ipv4Addr};
 
     fn localhost(port: u16) -> SocketAddr {
@@ -307,6 +787,43 @@ mod tests {
         assert_ne!(addr.port(), 0, "OS muss einen Port zuweisen");
     }
 
+    #[tokio::test]
+    async fn voice_server_dscp_konfiguration() {
+        let mut config = VoiceServerConfig::neu(localhost(0));
+        assert_eq!(config.dscp, Some(qos::DSCP_EF));
+
+        config.dscp = None;
+        let server = VoiceServer::binden(config, ChannelRouter::neu(), VoiceState::neu())
+            .await
+            .unwrap();
+        assert_eq!(server.qos_status(), &QosStatus::Deaktiviert);
+    }
+
+    #[tokio::test]
+    async fn voice_server_puffer_konfiguration() {
+        let mut config = VoiceServerConfig::neu(localhost(0));
+        assert_eq!(config.empfangspuffer, None);
+
+        config.empfangspuffer = Some(64 * 1024);
+        config.sendepuffer = Some(96 * 1024);
+        let server = VoiceServer::binden(config, ChannelRouter::neu(), VoiceState::neu())
+            .await
+            .unwrap();
+        let puffer = server.socket_puffer();
+        assert!(puffer.empfang >= 64 * 1024);
+        assert!(puffer.senden >= 96 * 1024);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn voice_server_markiert_auf_loopback() {
+        let config = VoiceServerConfig::neu(localhost(0));
+        let server = VoiceServer::binden(config, ChannelRouter::neu(), VoiceState::neu())
+            .await
+            .unwrap();
+        assert!(server.qos_status().ist_aktiv());
+    }
+
     #[tokio::test]
     async fn voice_server_paket_round_trip() {
         // Server starten
@@ -364,6 +881,534 @@ mod tests {
         recv_task.await.unwrap();
     }
 
+    #[tokio::test]
+    async fn hello_schaltet_endpunkt_frei_und_zaehlt_verworfene() {
+        let router = ChannelRouter::neu();
+        let state = VoiceState::neu();
+        let server = VoiceServer::binden(
+            VoiceServerConfig::neu(localhost(0)),
+            router.clone(),
+            state.clone(),
+        )
+        .await
+        .unwrap();
+        let server_addr = server.lokale_adresse().unwrap();
+
+        let sender = UserId::new();
+        let hoerer = UserId::new();
+        let kanal = ChannelId::new();
+        let nonce = vec![9u8; 32];
+        let sender_sock = UdpSocket::bind(localhost(0)).await.unwrap();
+        let hoerer_sock = UdpSocket::bind(localhost(0)).await.unwrap();
+        // Ausgehandelt war Port 0: die echte Adresse lernt der Server erst
+        state.client_anmelden(sender, 0x1111, localhost(0), nonce.clone());
+        state.client_registrieren(hoerer, 0x2222, hoerer_sock.local_addr().unwrap());
+        let _rx_sender = router.kanal_beitreten(sender, kanal, localhost(0));
+        let mut rx_hoerer =
+            router.kanal_beitreten(hoerer, kanal, hoerer_sock.local_addr().unwrap());
+
+        let (shutdown_tx, shutdown_rx) = tokio::sync::oneshot::channel::<()>();
+        let server = Arc::new(server);
+        let server_clone = Arc::clone(&server);
+        let recv_task = tokio::spawn(async move {
+            server_clone.empfangs_loop_starten(shutdown_rx).await;
+        });
+
+        // Vor dem Hello: Paket verworfen; falsches Hello und fremde SSRC ebenso
+        let senden = |daten: Vec<u8>| {
+            let sock = &sender_sock;
+            async move { sock.send_to(&daten, server_addr).await.unwrap() }
+        };
+        senden(make_paket(1, 0x1111).encode()).await;
+        senden(HelloPaket::neu(0x1111, 0, &[1u8; 32]).encode().to_vec()).await;
+        senden(HelloPaket::neu(0x3333, 0, &nonce).encode().to_vec()).await;
+        // Gueltiges Hello wird bestaetigt, danach wird weitergeleitet
+        senden(HelloPaket::neu(0x1111, 1, &nonce).encode().to_vec()).await;
+
+        let mut buf = [0u8; 64];
+        let (len, _) = tokio::time::timeout(
+            std::time::Duration::from_secs(1),
+            sender_sock.recv_from(&mut buf),
+        )
+        .await
+        .expect("Hello muss bestaetigt werden")
+        .unwrap();
+        let bestaetigung = HelloPaket::decode(&buf[..len]).unwrap();
+        assert!(bestaetigung.antwort);
+        assert_eq!(bestaetigung.versuch, 1);
+
+        senden(make_paket(2, 0x1111).encode()).await;
+        // Mitgeschnittenes Hello von fremder Adresse verlegt den Endpunkt nicht
+        let angreifer = UdpSocket::bind(localhost(0)).await.unwrap();
+        angreifer
+            .send_to(&HelloPaket::neu(0x1111, 1, &nonce).encode(), server_addr)
+            .await
+            .unwrap();
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        let _ = shutdown_tx.send(());
+        recv_task.await.unwrap();
+
+        let weitergeleitet = rx_hoerer
+            .try_recv()
+            .expect("Paket nach Hello weitergeleitet");
+        assert_eq!(
+            VoicePacket::decode(&weitergeleitet)
+                .unwrap()
+                .header
+                .sequence,
+            2
+        );
+        assert!(rx_hoerer.try_recv().is_err());
+        assert_eq!(server.unbekannt_verworfen(), 2);
+        assert_eq!(server.hello_abgelehnt(), 2);
+        assert_eq!(
+            state.user_id_von_endpunkt(&sender_sock.local_addr().unwrap()),
+            Some(sender)
+        );
+        assert!(state
+            .user_id_von_endpunkt(&angreifer.local_addr().unwrap())
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn uplink_verlust_wird_pro_absender_gezaehlt() {
+        let config = VoiceServerConfig::neu(localhost(0));
+        let router = ChannelRouter::neu();
+        let state = VoiceState::neu();
+
+        let server = VoiceServer::binden(config, router.clone(), state.clone())
+            .await
+            .expect("Server muss binden koennen");
+        let server_addr = server.lokale_adresse().unwrap();
+
+        let uid1 = UserId::new();
+        let uid2 = UserId::new();
+        let kanal = ChannelId::new();
+
+        let client1_sock = UdpSocket::bind(localhost(0)).await.unwrap();
+        let client2_sock = UdpSocket::bind(localhost(0)).await.unwrap();
+        state.client_registrieren(uid1, 0x1111, client1_sock.local_addr().unwrap());
+        state.client_registrieren(uid2, 0x2222, client2_sock.local_addr().unwrap());
+        let _rx1 = router.kanal_beitreten(uid1, kanal, client1_sock.local_addr().unwrap());
+        let _rx2 = router.kanal_beitreten(uid2, kanal, client2_sock.local_addr().unwrap());
+
+        let (shutdown_tx, shutdown_rx) = tokio::sync::oneshot::channel::<()>();
+        let server = Arc::new(server);
+        let server_clone = Arc::clone(&server);
+        let recv_task = tokio::spawn(async move {
+            server_clone.empfangs_loop_starten(shutdown_rx).await;
+        });
+
+        // Synthetische Strecke: Pakete 3 und 7 von Client 1 gehen verloren
+        for seq in (0..10).filter(|s| *s != 3 && *s != 7) {
+            let daten = make_paket(seq, 0x1111).encode();
+            client1_sock.send_to(&daten, server_addr).await.unwrap();
+        }
+        // Client 2 sendet verlustfrei
+        for seq in 0..5 {
+            let daten = make_paket(seq, 0x2222).encode();
+            client2_sock.send_to(&daten, server_addr).await.unwrap();
+        }
+
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+        let _ = shutdown_tx.send(());
+        recv_task.await.unwrap();
+
+        let uplink1 = state.client_state(&uid1).unwrap().uplink;
+        assert_eq!(uplink1.empfangen(), 8);
+        assert_eq!(uplink1.verloren(), 2);
+
+        let uplink2 = state.client_state(&uid2).unwrap().uplink;
+        assert_eq!(uplink2.empfangen(), 5);
+        assert_eq!(uplink2.verloren(), 0);
+    }
+
+    #[tokio::test]
+    async fn verspaetete_pakete_werden_nicht_weitergeleitet() {
+        let mut config = VoiceServerConfig::neu(localhost(0));
+        config.paket_ttl = Some(std::time::Duration::from_millis(200));
+        let router = ChannelRouter::neu();
+        let state = VoiceState::neu();
+        let server = VoiceServer::binden(config, router.clone(), state.clone())
+            .await
+            .unwrap();
+        let server_addr = server.lokale_adresse().unwrap();
+
+        let sender = UserId::new();
+        let hoerer = UserId::new();
+        let kanal = ChannelId::new();
+        let sender_sock = UdpSocket::bind(localhost(0)).await.unwrap();
+        let hoerer_sock = UdpSocket::bind(localhost(0)).await.unwrap();
+        state.client_registrieren(sender, 0x1111, sender_sock.local_addr().unwrap());
+        state.client_registrieren(hoerer, 0x2222, hoerer_sock.local_addr().unwrap());
+        let _rx_sender = router.kanal_beitreten(sender, kanal, sender_sock.local_addr().unwrap());
+        let mut rx_hoerer =
+            router.kanal_beitreten(hoerer, kanal, hoerer_sock.local_addr().unwrap());
+
+        let (shutdown_tx, shutdown_rx) = tokio::sync::oneshot::channel::<()>();
+        let server = Arc::new(server);
+        let server_clone = Arc::clone(&server);
+        let recv_task = tokio::spawn(async move {
+            server_clone.empfangs_loop_starten(shutdown_rx).await;
+        });
+
+        // Live-Strom steht bei Paket 40, dann trifft ein Burst aus der
+        // Sende-Queue ein, der 400-600 ms hinterherhinkt
+        let senden = |seq: u32| {
+            let daten = make_paket(seq, 0x1111).encode();
+            let sock = &sender_sock;
+            async move { sock.send_to(&daten, server_addr).await.unwrap() }
+        };
+        senden(40).await;
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        for seq in 10..20 {
+            senden(seq).await;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        senden(41).await;
+
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+        let _ = shutdown_tx.send(());
+        recv_task.await.unwrap();
+
+        let mut weitergeleitet = Vec::new();
+        while let Ok(daten) = rx_hoerer.try_recv() {
+            weitergeleitet.push(VoicePacket::decode(&daten).unwrap().header.sequence);
+        }
+        assert_eq!(weitergeleitet, vec![40, 41]);
+        assert_eq!(server.veraltet_verworfen(), 10);
+
+        let client = state.client_state(&sender).unwrap();
+        assert_eq!(client.veraltet_verworfen, 10);
+        // Verworfene Pakete sind beim Server angekommen: kein Uplink-Verlust
+        assert_eq!(client.uplink.empfangen(), 12);
+    }
+
+    #[tokio::test]
+    async fn pakete_von_nur_zuhoerern_werden_nie_weitergeleitet() {
+        let router = ChannelRouter::neu();
+        let state = VoiceState::neu();
+        let server = VoiceServer::binden(
+            VoiceServerConfig::neu(localhost(0)),
+            router.clone(),
+            state.clone(),
+        )
+        .await
+        .unwrap();
+        let server_addr = server.lokale_adresse().unwrap();
+
+        let zuhoerer = UserId::new();
+        let sprecher = UserId::new();
+        let kanal = ChannelId::new();
+        let zuhoerer_sock = UdpSocket::bind(localhost(0)).await.unwrap();
+        let sprecher_sock = UdpSocket::bind(localhost(0)).await.unwrap();
+        state.client_registrieren(zuhoerer, 0x1111, zuhoerer_sock.local_addr().unwrap());
+        state.client_registrieren(sprecher, 0x2222, sprecher_sock.local_addr().unwrap());
+        state.senden_erlauben(&zuhoerer, false);
+        let mut rx_zuhoerer =
+            router.kanal_beitreten(zuhoerer, kanal, zuhoerer_sock.local_addr().unwrap());
+        let mut rx_sprecher =
+            router.kanal_beitreten(sprecher, kanal, sprecher_sock.local_addr().unwrap());
+
+        let (shutdown_tx, shutdown_rx) = tokio::sync::oneshot::channel::<()>();
+        let server = Arc::new(server);
+        let server_clone = Arc::clone(&server);
+        let recv_task = tokio::spawn(async move {
+            server_clone.empfangs_loop_starten(shutdown_rx).await;
+        });
+
+        // Versehentlich offenes Mikrofon beim Zuhoerer, inkl. Speaking-Flag
+        for seq in 0..5 {
+            let mut paket = make_paket(seq, 0x1111);
+            if seq == 0 {
+                paket.header.flags |= speakeasy_protocol::voice::VoiceFlags::SPEAKING_START;
+            }
+            zuhoerer_sock
+                .send_to(&paket.encode(), server_addr)
+                .await
+                .unwrap();
+        }
+        for seq in 0..3 {
+            let daten = make_paket(seq, 0x2222).encode();
+            sprecher_sock.send_to(&daten, server_addr).await.unwrap();
+        }
+
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+        let _ = shutdown_tx.send(());
+        recv_task.await.unwrap();
+
+        let mut beim_sprecher = 0;
+        while rx_sprecher.try_recv().is_ok() {
+            beim_sprecher += 1;
+        }
+        let mut beim_zuhoerer = 0;
+        while rx_zuhoerer.try_recv().is_ok() {
+            beim_zuhoerer += 1;
+        }
+        assert_eq!(beim_sprecher, 0, "Zuhoerer-Pakete duerfen nie ankommen");
+        assert_eq!(beim_zuhoerer, 3);
+        assert_eq!(server.nur_hoeren_verworfen(), 5);
+
+        let client = state.client_state(&zuhoerer).unwrap();
+        assert_eq!(client.nur_hoeren_verworfen, 5);
+        assert!(!client.spricht);
+        assert_eq!(client.uplink.empfangen(), 0);
+    }
+
+    #[tokio::test]
+    async fn sprachpakete_melden_aktivitaet() {
+        let router = ChannelRouter::neu();
+        let state = VoiceState::neu();
+        let mut server =
+            VoiceServer::binden(VoiceServerConfig::neu(localhost(0)), router, state.clone())
+                .await
+                .unwrap();
+        let tracker = AktivitaetsTracker::neu();
+        server.aktivitaet_verfolgen(tracker.clone());
+        let server_addr = server.lokale_adresse().unwrap();
+
+        let stumm = UserId::new();
+        let sprecher = UserId::new();
+        let stumm_sock = UdpSocket::bind(localhost(0)).await.unwrap();
+        let sprecher_sock = UdpSocket::bind(localhost(0)).await.unwrap();
+        state.client_registrieren(stumm, 0x1111, stumm_sock.local_addr().unwrap());
+        state.client_registrieren(sprecher, 0x2222, sprecher_sock.local_addr().unwrap());
+
+        let (shutdown_tx, shutdown_rx) = tokio::sync::oneshot::channel::<()>();
+        let server = Arc::new(server);
+        let server_clone = Arc::clone(&server);
+        let recv_task = tokio::spawn(async move {
+            server_clone.empfangs_loop_starten(shutdown_rx).await;
+        });
+
+        // Ohne Speaking-Flag zaehlt ein Paket nicht als Aktivitaet
+        let daten = make_paket(0, 0x1111).encode();
+        stumm_sock.send_to(&daten, server_addr).await.unwrap();
+
+        let mut paket = make_paket(0, 0x2222);
+        paket.header.flags |= speakeasy_protocol::voice::VoiceFlags::SPEAKING_START;
+        sprecher_sock
+            .send_to(&paket.encode(), server_addr)
+            .await
+            .unwrap();
+
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        let _ = shutdown_tx.send(());
+        recv_task.await.unwrap();
+
+        assert!(tracker.letzte_aktivitaet(&stumm).is_none());
+        assert!(tracker.letzte_aktivitaet(&sprecher).is_some());
+        assert!(state
+            .sprecher()
+            .spricht(&sprecher, tokio::time::Instant::now()));
+    }
+
+    type Empfangsergebnis = std::io::Result<(Vec<u8>, SocketAddr)>;
+
+    /// Liefert vorgegebene Ergebnisse, danach wartet sie fuer immer
+    struct SkriptQuelle(parking_lot::Mutex<VecDeque<Empfangsergebnis>>);
+
+    impl DatagrammQuelle for SkriptQuelle {
+        async fn empfangen(&self, buf: &mut [u8]) -> std::io::Result<(usize, SocketAddr)> {
+            let naechstes = self.0.lock().pop_front();
+            match naechstes {
+                Some(Ok((daten, absender))) => {
+                    buf[..daten.len()].copy_from_slice(&daten);
+                    Ok((daten.len(), absender))
+                }
+                Some(Err(e)) => Err(e),
+                None => std::future::pending().await,
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn icmp_fehler_beenden_die_empfangs_loop_nicht() {
+        let state = VoiceState::neu();
+        let server = VoiceServer::binden(
+            VoiceServerConfig::neu(localhost(0)),
+            ChannelRouter::neu(),
+            state.clone(),
+        )
+        .await
+        .unwrap();
+        let absender = localhost(40_000);
+        let uid = UserId::new();
+        state.client_registrieren(uid, 0x1111, absender);
+
+        // Burst wie unter Windows nach einem verschwundenen Client, dazwischen
+        // ein gueltiges Paket
+        let mut skript: VecDeque<Empfangsergebnis> = VecDeque::new();
+        for _ in 0..100 {
+            skript.push_back(Err(std::io::ErrorKind::ConnectionReset.into()));
+        }
+        skript.push_back(Ok((make_paket(0, 0x1111).encode().to_vec(), absender)));
+        skript.push_back(Err(std::io::ErrorKind::ConnectionRefused.into()));
+        skript.push_back(Err(std::io::ErrorKind::Interrupted.into()));
+        skript.push_back(Err(std::io::ErrorKind::Other.into()));
+        skript.push_back(Ok((make_paket(1, 0x1111).encode().to_vec(), absender)));
+        let quelle = SkriptQuelle(parking_lot::Mutex::new(skript));
+
+        let (shutdown_tx, shutdown_rx) = tokio::sync::oneshot::channel::<()>();
+        let server = Arc::new(server);
+        let server_clone = Arc::clone(&server);
+        let recv_task = tokio::spawn(async move {
+            server_clone.empfangs_loop(&quelle, shutdown_rx).await;
+        });
+
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        assert!(
+            !recv_task.is_finished(),
+            "Loop darf nur beim Shutdown enden"
+        );
+        let _ = shutdown_tx.send(());
+        recv_task.await.unwrap();
+
+        assert_eq!(server.empfang_unerreichbar(), 101);
+        assert_eq!(state.client_state(&uid).unwrap().uplink.empfangen(), 2);
+    }
+
+    #[tokio::test]
+    async fn empfangs_loop_meldet_jitter_metriken() {
+        let state = VoiceState::neu();
+        let mut server = VoiceServer::binden(
+            VoiceServerConfig::neu(localhost(0)),
+            ChannelRouter::neu(),
+            state.clone(),
+        )
+        .await
+        .unwrap();
+        let sammler = VoiceMetricsCollector::neu();
+        server.metriken_erfassen(sammler.clone());
+        let absender = localhost(40_001);
+        state.client_registrieren(UserId::new(), 0x1111, absender);
+
+        // Pakete 3 und 7 fehlen, Paket 5 kommt doppelt
+        let mut skript: VecDeque<Empfangsergebnis> = VecDeque::new();
+        for seq in (0..12).filter(|s| *s != 3 && *s != 7) {
+            skript.push_back(Ok((make_paket(seq, 0x1111).encode().to_vec(), absender)));
+            if seq == 5 {
+                skript.push_back(Ok((make_paket(seq, 0x1111).encode().to_vec(), absender)));
+            }
+        }
+        let quelle = SkriptQuelle(parking_lot::Mutex::new(skript));
+
+        let (shutdown_tx, shutdown_rx) = tokio::sync::oneshot::channel::<()>();
+        let server = Arc::new(server);
+        let server_clone = Arc::clone(&server);
+        let recv_task = tokio::spawn(async move {
+            server_clone.empfangs_loop(&quelle, shutdown_rx).await;
+        });
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        let _ = shutdown_tx.send(());
+        recv_task.await.unwrap();
+
+        let snapshot = sammler.abtasten(&state);
+        assert_eq!(snapshot.ssrcs.len(), 1);
+        let statistik = &snapshot.ssrcs[0].statistik;
+        assert_eq!(snapshot.ssrcs[0].ssrc, 0x1111);
+        assert_eq!(statistik.empfangen, 11);
+        assert_eq!(statistik.duplikate, 1);
+        assert_eq!(statistik.verloren, 2);
+        assert!(statistik.fuellstand > 0);
+    }
+
+    #[tokio::test]
+    async fn empfangs_loop_schlaegt_ohne_verkehr() {
+        let mut server = VoiceServer::binden(
+            VoiceServerConfig::neu(localhost(0)),
+            ChannelRouter::neu(),
+            VoiceState::neu(),
+        )
+        .await
+        .unwrap();
+        let herzschlag = Arc::new(AtomicU64::new(0));
+        server.herzschlag_melden(Arc::clone(&herzschlag));
+
+        let (shutdown_tx, shutdown_rx) = tokio::sync::oneshot::channel::<()>();
+        let server = Arc::new(server);
+        let server_clone = Arc::clone(&server);
+        let recv_task = tokio::spawn(async move {
+            server_clone.empfangs_loop_starten(shutdown_rx).await;
+        });
+
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        let erster = herzschlag.load(Ordering::Relaxed);
+        assert!(erster > 0);
+        tokio::time::sleep(HERZSCHLAG_INTERVALL + Duration::from_millis(200)).await;
+        assert!(herzschlag.load(Ordering::Relaxed) > erster);
+
+        let _ = shutdown_tx.send(());
+        recv_task.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn pings_werden_ohne_session_gedrosselt_beantwortet() {
+        let mut config = VoiceServerConfig::neu(localhost(0));
+        config.ping = Some(PingLimits {
+            pro_sekunde: 0.1,
+            burst: 3,
+            max_quellen: 16,
+        });
+        let state = VoiceState::neu();
+        let server = VoiceServer::binden(config, ChannelRouter::neu(), state.clone())
+            .await
+            .unwrap();
+        let server_addr = server.lokale_adresse().unwrap();
+
+        let (shutdown_tx, shutdown_rx) = tokio::sync::oneshot::channel::<()>();
+        let server = Arc::new(server);
+        let server_clone = Arc::clone(&server);
+        let recv_task = tokio::spawn(async move {
+            server_clone.empfangs_loop_starten(shutdown_rx).await;
+        });
+
+        let client = UdpSocket::bind(localhost(0)).await.unwrap();
+        for seq in 0..5 {
+            let anfrage = PingPaket::anfrage(seq, 1000 + seq).encode();
+            client.send_to(&anfrage, server_addr).await.unwrap();
+        }
+
+        let mut buf = [0u8; UDP_BUFFER_SIZE];
+        for seq in 0..3 {
+            let (len, _) = tokio::time::timeout(Duration::from_secs(1), client.recv_from(&mut buf))
+                .await
+                .expect("Ping-Antwort erwartet")
+                .unwrap();
+            let antwort = PingPaket::decode(&buf[..len]).unwrap();
+            assert!(antwort.antwort);
+            assert_eq!(antwort.sequenz, seq);
+            assert_eq!(antwort.client_zeit, 1000 + seq);
+            assert!(antwort.server_zeit_ms > 0);
+        }
+        // Ueberzaehlige Pings bleiben unbeantwortet
+        assert!(
+            tokio::time::timeout(Duration::from_millis(100), client.recv_from(&mut buf))
+                .await
+                .is_err()
+        );
+
+        let _ = shutdown_tx.send(());
+        recv_task.await.unwrap();
+
+        let reflektor = server.ping_reflektor().unwrap();
+        assert_eq!(reflektor.beantwortet(), 3);
+        assert_eq!(reflektor.gedrosselt(), 2);
+        assert_eq!(state.client_anzahl(), 0);
+    }
+
+    #[test]
+    fn ziel_pause_laeuft_ab() {
+        let start = Instant::now();
+        let mut pause = ZielPause::default();
+        assert!(!pause.aktiv(start));
+
+        pause.melden(start);
+        assert!(pause.aktiv(start + ZIEL_PAUSE / 2));
+        assert!(!pause.aktiv(start + ZIEL_PAUSE));
+        assert!(!pause.aktiv(start + ZIEL_PAUSE / 2));
+    }
+
     #[test]
     fn voice_paket_encode_decode_roundtrip() {
         let original = make_paket(42, 0xDEAD);
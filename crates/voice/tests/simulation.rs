@@ -0,0 +1,775 @@
+//! Deterministische Simulation der Voice-Strecke ohne Sockets
+//!
+//! Skriptbare Sender -> Netz (Verlust, Jitter, Stau) -> Frische-Pruefung
+//! (Policer) -> echter `ChannelRouter` -> Jitter Buffer und PLC pro
+//! Empfaenger. Die Zeit stammt aus einer virtuellen Uhr, Zufall aus einem
+//! Generator mit festem Startwert: jeder Lauf liefert dieselben Zahlen.
+//!
+//! Jedes Paket traegt im Payload seine Sendezeit und die urspruengliche
+//! Sequenznummer, damit auch umgeschriebene Header auswertbar bleiben.
+
+use speakeasy_core::types::{ChannelId, UserId};
+use speakeasy_protocol::voice::{VoiceFlags, VoicePacket};
+use speakeasy_voice::frische::{Frische, FrischePruefung, Takt};
+use speakeasy_voice::jitter_buffer::{AdaptiveJitterBuffer, JitterBufferStatistik};
+use speakeasy_voice::plc::{PacketLossConcealer, PlcErgebnis, PlcStatistik};
+use speakeasy_voice::{ChannelRouter, Resequenzierung};
+use std::collections::{BTreeMap, HashMap};
+use std::net::SocketAddr;
+use std::ops::Range;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
+
+/// Ticks pro Frame (20 ms bei 48 kHz)
+const TICKS_PRO_FRAME: u32 = 960;
+
+/// Abspieltakt der Empfaenger
+const FRAME_US: u64 = 20_000;
+
+/// Schrittweite der virtuellen Uhr
+const SCHRITT_US: u64 = 1_000;
+
+// ---------------------------------------------------------------------------
+// Virtuelle Uhr und Zufall
+// ---------------------------------------------------------------------------
+
+/// Virtuelle Uhr: laeuft nur, wenn die Simulation sie vorstellt
+struct VirtuelleUhr {
+    basis: Instant,
+    jetzt_us: u64,
+}
+
+impl VirtuelleUhr {
+    fn neu() -> Self {
+        Self {
+            basis: Instant::now(),
+            jetzt_us: 0,
+        }
+    }
+
+    /// Zeitpunkt als `Instant` (fuer Komponenten, die echte Zeitpunkte erwarten)
+    fn instant(&self, zeit_us: u64) -> Instant {
+        self.basis + Duration::from_micros(zeit_us)
+    }
+
+    fn vorstellen(&mut self, us: u64) {
+        self.jetzt_us += us;
+    }
+}
+
+/// xorshift64* – reproduzierbar und ohne zusaetzliche Abhaengigkeit
+struct Zufall(u64);
+
+impl Zufall {
+    fn neu(startwert: u64) -> Self {
+        Self(startwert.max(1))
+    }
+
+    fn naechste(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+
+    /// Gleichverteilt in [0, 1)
+    fn anteil(&mut self) -> f64 {
+        (self.naechste() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// Gleichverteilt in [0, max]
+    fn bis(&mut self, max: u64) -> u64 {
+        if max == 0 {
+            0
+        } else {
+            self.naechste() % (max + 1)
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Sender-Skripte
+// ---------------------------------------------------------------------------
+
+/// Verlustmuster auf dem Weg zum Server
+#[derive(Clone)]
+enum Verlust {
+    Keiner,
+    /// Zufaelliger Verlust mit fester Rate
+    Rate(f64),
+    /// Verlust ganzer Bloecke (Sequenznummern)
+    Bloecke(Vec<Range<u32>>),
+}
+
+/// Verteilung der Zusatzverzoegerung im Netz
+#[derive(Clone, Copy)]
+enum Jitter {
+    Keiner,
+    /// Gleichverteilt in [0, max_ms]
+    Gleichverteilt {
+        max_ms: u64,
+    },
+}
+
+/// Zeitlicher Ablauf eines Senders
+#[derive(Clone)]
+struct SenderSkript {
+    ssrc: u32,
+    pakete_pro_sekunde: u32,
+    laufzeit_ms: Range<u64>,
+    grundverzoegerung_ms: u64,
+    verlust: Verlust,
+    jitter: Jitter,
+    /// Stummschaltungen: keine Pakete, der Zeitstempel steht
+    pausen: Vec<Range<u64>>,
+    /// Stau beim Sender: Pakete aus diesem Fenster gehen erst an dessen Ende raus
+    stau: Option<Range<u64>>,
+}
+
+impl SenderSkript {
+    fn neu(ssrc: u32) -> Self {
+        Self {
+            ssrc,
+            pakete_pro_sekunde: 50,
+            laufzeit_ms: 0..2_000,
+            grundverzoegerung_ms: 30,
+            verlust: Verlust::Keiner,
+            jitter: Jitter::Keiner,
+            pausen: Vec::new(),
+            stau: None,
+        }
+    }
+
+    fn mit_laufzeit(mut self, laufzeit_ms: Range<u64>) -> Self {
+        self.laufzeit_ms = laufzeit_ms;
+        self
+    }
+
+    fn mit_verlust(mut self, verlust: Verlust) -> Self {
+        self.verlust = verlust;
+        self
+    }
+
+    fn mit_jitter(mut self, jitter: Jitter) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    fn mit_pause(mut self, pause_ms: Range<u64>) -> Self {
+        self.pausen.push(pause_ms);
+        self
+    }
+
+    fn mit_stau(mut self, stau_ms: Range<u64>) -> Self {
+        self.stau = Some(stau_ms);
+        self
+    }
+
+    /// Erzeugt alle beim Server ankommenden Pakete mit Ankunftszeit (us)
+    fn ankuenfte(&self, zufall: &mut Zufall) -> Vec<(u64, VoicePacket)> {
+        let abstand_us = 1_000_000 / u64::from(self.pakete_pro_sekunde);
+        let mut ankuenfte = Vec::new();
+        let (mut sequenz, mut zeitstempel) = (0u32, 0u32);
+        let mut sprechbeginn = true;
+
+        let mut zeit_us = self.laufzeit_ms.start * 1_000;
+        while zeit_us < self.laufzeit_ms.end * 1_000 {
+            let zeit_ms = zeit_us / 1_000;
+            if self.pausen.iter().any(|p| p.contains(&zeit_ms)) {
+                sprechbeginn = true;
+                zeit_us += abstand_us;
+                continue;
+            }
+
+            let mut nutzdaten = zeit_us.to_le_bytes().to_vec();
+            nutzdaten.extend_from_slice(&sequenz.to_le_bytes());
+            nutzdaten.resize(60, 0xAB);
+            let mut paket = VoicePacket::neu_audio(sequenz, zeitstempel, self.ssrc, nutzdaten);
+            if sprechbeginn {
+                paket.header.flags |= VoiceFlags::SPEAKING_START;
+                sprechbeginn = false;
+            }
+
+            let verloren = match &self.verlust {
+                Verlust::Keiner => false,
+                Verlust::Rate(rate) => zufall.anteil() < *rate,
+                Verlust::Bloecke(bloecke) => bloecke.iter().any(|b| b.contains(&sequenz)),
+            };
+            if !verloren {
+                let abgang_us = match &self.stau {
+                    Some(stau) if stau.contains(&zeit_ms) => stau.end * 1_000,
+                    _ => zeit_us,
+                };
+                let jitter_ms = match self.jitter {
+                    Jitter::Keiner => 0,
+                    Jitter::Gleichverteilt { max_ms } => zufall.bis(max_ms),
+                };
+                let ankunft_us = abgang_us + (self.grundverzoegerung_ms + jitter_ms) * 1_000;
+                ankuenfte.push((ankunft_us, paket));
+            }
+
+            sequenz = sequenz.wrapping_add(1);
+            zeitstempel = zeitstempel.wrapping_add(TICKS_PRO_FRAME);
+            zeit_us += abstand_us;
+        }
+        ankuenfte
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Empfaenger
+// ---------------------------------------------------------------------------
+
+/// Abgespielter Frame eines Stroms
+#[derive(Debug, Clone, Copy)]
+struct Frame {
+    /// Sequenznummer wie beim Empfaenger angekommen (ggf. umgeschrieben)
+    sequenz: u32,
+    /// Urspruengliche Sequenznummer (`None` = von der PLC erzeugt)
+    original: Option<u32>,
+    /// Sendezeit bis Wiedergabe (nur Originale)
+    latenz_us: Option<u64>,
+}
+
+/// Wiedergabe eines Absenders beim Empfaenger
+struct Strom {
+    puffer: AdaptiveJitterBuffer,
+    plc: PacketLossConcealer,
+    naechster_takt_us: u64,
+    frames: Vec<Frame>,
+    unterlaeufe: u64,
+}
+
+impl Strom {
+    fn neu(start_us: u64) -> Self {
+        Self {
+            puffer: AdaptiveJitterBuffer::standard(),
+            plc: PacketLossConcealer::neu(),
+            naechster_takt_us: start_us,
+            frames: Vec::new(),
+            unterlaeufe: 0,
+        }
+    }
+
+    fn abspielen(&mut self, jetzt_us: u64) {
+        let Some(paket) = self.puffer.pop() else {
+            self.unterlaeufe += 1;
+            return;
+        };
+        for ergebnis in self.plc.verarbeiten(paket) {
+            let Some(paket) = ergebnis.paket() else {
+                continue;
+            };
+            let frame = match ergebnis {
+                PlcErgebnis::Original(_) => {
+                    let gesendet = u64::from_le_bytes(paket.payload[..8].try_into().unwrap());
+                    let original = u32::from_le_bytes(paket.payload[8..12].try_into().unwrap());
+                    Frame {
+                        sequenz: paket.header.sequence,
+                        original: Some(original),
+                        latenz_us: Some(jetzt_us - gesendet),
+                    }
+                }
+                _ => Frame {
+                    sequenz: paket.header.sequence,
+                    original: None,
+                    latenz_us: None,
+                },
+            };
+            self.frames.push(frame);
+        }
+    }
+}
+
+struct Empfaenger {
+    user_id: UserId,
+    rx: mpsc::Receiver<Arc<Vec<u8>>>,
+    stroeme: BTreeMap<u32, Strom>,
+}
+
+impl Empfaenger {
+    /// Holt alle weitergeleiteten Pakete aus der Send-Queue
+    fn zustellen(&mut self, jetzt_us: u64) {
+        while let Ok(daten) = self.rx.try_recv() {
+            let paket = VoicePacket::decode(&daten).expect("Router liefert gueltige Pakete");
+            self.stroeme
+                .entry(paket.header.ssrc)
+                .or_insert_with(|| Strom::neu(jetzt_us))
+                .puffer
+                .push(paket);
+        }
+    }
+
+    fn takt(&mut self, jetzt_us: u64) {
+        for strom in self.stroeme.values_mut() {
+            while strom.naechster_takt_us <= jetzt_us {
+                strom.abspielen(jetzt_us);
+                strom.naechster_takt_us += FRAME_US;
+            }
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Simulation
+// ---------------------------------------------------------------------------
+
+/// Eingriffe waehrend des Laufs
+#[derive(Clone, Copy)]
+enum Aktion {
+    NotfallAn,
+    NotfallAus,
+}
+
+/// Aufbau eines Szenarios: ein Kanal, Sender, Empfaenger, optional Policer
+struct Simulation {
+    router: ChannelRouter,
+    kanal: ChannelId,
+    sender: Vec<(UserId, SenderSkript)>,
+    empfaenger: Vec<Empfaenger>,
+    ttl: Option<Duration>,
+    aktionen: Vec<(u64, Aktion)>,
+    dauer_ms: u64,
+    startwert: u64,
+}
+
+impl Simulation {
+    fn neu(dauer_ms: u64) -> Self {
+        Self {
+            router: ChannelRouter::neu(),
+            kanal: ChannelId::new(),
+            sender: Vec::new(),
+            empfaenger: Vec::new(),
+            ttl: None,
+            aktionen: Vec::new(),
+            dauer_ms,
+            startwert: 0x5EED,
+        }
+    }
+
+    fn endpunkt(&self) -> SocketAddr {
+        let port = 40_000 + (self.sender.len() + self.empfaenger.len()) as u16;
+        SocketAddr::from(([127, 0, 0, 1], port))
+    }
+
+    fn sender(&mut self, skript: SenderSkript) -> UserId {
+        let user_id = UserId::new();
+        // Die Queue eines Senders wird nie gelesen: Sender hoeren nichts
+        let _rx = self
+            .router
+            .kanal_beitreten(user_id, self.kanal, self.endpunkt());
+        self.sender.push((user_id, skript));
+        user_id
+    }
+
+    fn empfaenger(&mut self, modus: Resequenzierung) -> usize {
+        let user_id = UserId::new();
+        let rx = self
+            .router
+            .kanal_beitreten(user_id, self.kanal, self.endpunkt());
+        self.router.resequenzierung_setzen(user_id, modus);
+        self.empfaenger.push(Empfaenger {
+            user_id,
+            rx,
+            stroeme: BTreeMap::new(),
+        });
+        self.empfaenger.len() - 1
+    }
+
+    fn mit_policer(mut self, ttl: Duration) -> Self {
+        self.ttl = Some(ttl);
+        self
+    }
+
+    fn aktion(&mut self, zeit_ms: u64, aktion: Aktion) {
+        self.aktionen.push((zeit_ms * 1_000, aktion));
+    }
+
+    fn ausfuehren(mut self) -> Ergebnis {
+        let mut uhr = VirtuelleUhr::neu();
+        let mut zufall = Zufall::neu(self.startwert);
+
+        // Ankuenfte aller Sender nach Zeit ordnen (stabil: gleichzeitige
+        // Pakete eines Senders behalten ihre Reihenfolge)
+        let mut ankuenfte: Vec<(u64, UserId, VoicePacket)> = Vec::new();
+        for (user_id, skript) in &self.sender {
+            for (zeit, paket) in skript.ankuenfte(&mut zufall) {
+                ankuenfte.push((zeit, *user_id, paket));
+            }
+        }
+        ankuenfte.sort_by_key(|(zeit, _, _)| *zeit);
+        self.aktionen.sort_by_key(|(zeit, _)| *zeit);
+
+        let mut policer: HashMap<UserId, FrischePruefung> = HashMap::new();
+        let mut zaehler = Zaehler::default();
+        let (mut naechste_ankunft, mut naechste_aktion) = (0, 0);
+
+        while uhr.jetzt_us <= self.dauer_ms * 1_000 {
+            let jetzt = uhr.jetzt_us;
+            while let Some(&(zeit, aktion)) = self.aktionen.get(naechste_aktion) {
+                if zeit > jetzt {
+                    break;
+                }
+                match aktion {
+                    Aktion::NotfallAn => self.router.notfall().aktivieren(self.kanal, []),
+                    Aktion::NotfallAus => {
+                        self.router.notfall().deaktivieren(&self.kanal);
+                    }
+                }
+                naechste_aktion += 1;
+            }
+
+            while let Some((zeit, absender, paket)) = ankuenfte.get(naechste_ankunft) {
+                if *zeit > jetzt {
+                    break;
+                }
+                naechste_ankunft += 1;
+                zaehler.angekommen += 1;
+
+                if let Some(ttl) = self.ttl {
+                    let frische = policer.entry(*absender).or_default().pruefen(
+                        &paket.header,
+                        uhr.instant(*zeit),
+                        Takt::default(),
+                        ttl,
+                    );
+                    if let Frische::Veraltet { .. } = frische {
+                        zaehler.veraltet += 1;
+                        self.router.paket_unterdrueckt(&paket.header, absender);
+                        continue;
+                    }
+                }
+
+                if self.router.notfall_unterdrueckt(absender) {
+                    zaehler.notfall += 1;
+                }
+                if self.router.paket_weiterleiten(paket, absender) > 0 {
+                    zaehler.weitergeleitet += 1;
+                }
+            }
+
+            for empfaenger in &mut self.empfaenger {
+                empfaenger.zustellen(jetzt);
+                empfaenger.takt(jetzt);
+            }
+            uhr.vorstellen(SCHRITT_US);
+        }
+
+        let empfaenger = self
+            .empfaenger
+            .into_iter()
+            .map(|e| EmpfaengerErgebnis {
+                unterdrueckt: self.router.unterdrueckt_fuer(&e.user_id),
+                stroeme: e
+                    .stroeme
+                    .into_iter()
+                    .map(|(ssrc, s)| (ssrc, StromErgebnis::aus(s)))
+                    .collect(),
+            })
+            .collect();
+        Ergebnis {
+            zaehler,
+            empfaenger,
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Auswertung
+// ---------------------------------------------------------------------------
+
+/// Zaehler der Server-Seite
+#[derive(Debug, Default)]
+struct Zaehler {
+    angekommen: u64,
+    veraltet: u64,
+    notfall: u64,
+    weitergeleitet: u64,
+}
+
+#[derive(Debug)]
+struct StromErgebnis {
+    frames: Vec<Frame>,
+    unterlaeufe: u64,
+    jitter_buffer: JitterBufferStatistik,
+    plc: PlcStatistik,
+    /// Pakete, die am Ende noch im Puffer liegen (der Mindestfuellstand
+    /// haelt den letzten Frame eines Stroms zurueck)
+    rest: usize,
+}
+
+impl StromErgebnis {
+    fn aus(strom: Strom) -> Self {
+        Self {
+            jitter_buffer: strom.puffer.statistik().clone(),
+            plc: strom.plc.statistik().clone(),
+            rest: strom.puffer.fuellstand(),
+            frames: strom.frames,
+            unterlaeufe: strom.unterlaeufe,
+        }
+    }
+
+    /// Urspruengliche Sequenznummern der abgespielten Originale
+    fn originale(&self) -> Vec<u32> {
+        self.frames.iter().filter_map(|f| f.original).collect()
+    }
+
+    /// Spruenge in der Sequenz der abgespielten Frames (inkl. PLC)
+    fn spruenge(&self) -> usize {
+        self.frames
+            .windows(2)
+            .filter(|w| w[1].sequenz != w[0].sequenz.wrapping_add(1))
+            .count()
+    }
+
+    fn latenz_max_ms(&self) -> u64 {
+        self.latenz_max_ms_ab(0)
+    }
+
+    /// Hoechste Latenz der Originale ab der urspruenglichen Sequenznummer `ab`
+    fn latenz_max_ms_ab(&self, ab: u32) -> u64 {
+        self.frames
+            .iter()
+            .filter(|f| f.original.is_some_and(|o| o >= ab))
+            .filter_map(|f| f.latenz_us)
+            .max()
+            .unwrap_or(0)
+            / 1_000
+    }
+
+    fn latenz_mittel_ms(&self) -> f64 {
+        let latenzen: Vec<u64> = self.frames.iter().filter_map(|f| f.latenz_us).collect();
+        if latenzen.is_empty() {
+            return 0.0;
+        }
+        latenzen.iter().sum::<u64>() as f64 / latenzen.len() as f64 / 1_000.0
+    }
+}
+
+#[derive(Debug)]
+struct EmpfaengerErgebnis {
+    unterdrueckt: Vec<(u32, u64)>,
+    stroeme: BTreeMap<u32, StromErgebnis>,
+}
+
+#[derive(Debug)]
+struct Ergebnis {
+    zaehler: Zaehler,
+    empfaenger: Vec<EmpfaengerErgebnis>,
+}
+
+impl Ergebnis {
+    fn strom(&self, empfaenger: usize, ssrc: u32) -> &StromErgebnis {
+        &self.empfaenger[empfaenger].stroeme[&ssrc]
+    }
+}
+
+/// Prueft, dass Originale streng aufsteigend und ohne Duplikate abgespielt wurden
+fn assert_aufsteigend(originale: &[u32]) {
+    assert!(
+        originale.windows(2).all(|w| w[0] < w[1]),
+        "Originale nicht streng aufsteigend: {originale:?}"
+    );
+}
+
+// ---------------------------------------------------------------------------
+// Szenarien
+// ---------------------------------------------------------------------------
+
+#[test]
+fn verlustfreier_strom_kommt_lueckenlos_an() {
+    let mut sim = Simulation::neu(2_500);
+    sim.sender(SenderSkript::neu(1));
+    let hoerer = sim.empfaenger(Resequenzierung::Aus);
+    let ergebnis = sim.ausfuehren();
+
+    let strom = ergebnis.strom(hoerer, 1);
+    assert_eq!(strom.originale(), (0..99).collect::<Vec<_>>());
+    assert_eq!(strom.rest, 1);
+    assert_eq!(strom.spruenge(), 0);
+    assert_eq!(strom.plc.gesamt_verloren, 0);
+    assert_eq!(strom.jitter_buffer.verloren, 0);
+    // Grundverzoegerung 30 ms plus hoechstens zwei Frames Puffer
+    assert!(
+        strom.latenz_max_ms() <= 30 + 2 * 20 + 1,
+        "{}",
+        strom.latenz_max_ms()
+    );
+}
+
+#[test]
+fn burst_verlust_wird_von_plc_begrenzt_verdeckt() {
+    let mut sim = Simulation::neu(2_500);
+    sim.sender(SenderSkript::neu(1).mit_verlust(Verlust::Bloecke(vec![40..50, 70..71])));
+    let hoerer = sim.empfaenger(Resequenzierung::Aus);
+    let ergebnis = sim.ausfuehren();
+
+    let strom = ergebnis.strom(hoerer, 1);
+    let erwartet: Vec<u32> = (0..40).chain(50..70).chain(71..99).collect();
+    assert_eq!(strom.originale(), erwartet);
+    assert_eq!(strom.jitter_buffer.verloren, 11);
+    // PLC verdeckt hoechstens MAX_WIEDERHOLUNGEN + 1 Frames, danach Sprung;
+    // der einzelne Verlust bleibt vollstaendig verdeckt
+    assert_eq!(strom.plc.wiederholungen, 4);
+    assert_eq!(strom.plc.stille_eingefuegt, 1);
+    assert_eq!(strom.plc.gesamt_verloren, 5);
+    assert_eq!(strom.spruenge(), 1);
+    assert_eq!(ergebnis.zaehler.weitergeleitet, 89);
+}
+
+#[test]
+fn umsortierte_pakete_werden_in_reihenfolge_abgespielt() {
+    let mut sim = Simulation::neu(3_500);
+    sim.sender(
+        SenderSkript::neu(1)
+            .mit_laufzeit(0..3_000)
+            .mit_jitter(Jitter::Gleichverteilt { max_ms: 50 }),
+    );
+    let hoerer = sim.empfaenger(Resequenzierung::Aus);
+    let ergebnis = sim.ausfuehren();
+
+    let strom = ergebnis.strom(hoerer, 1);
+    assert_eq!(strom.jitter_buffer.empfangen, 150);
+    let originale = strom.originale();
+    assert_aufsteigend(&originale);
+    // Zu spaet eintreffende Pakete verwirft der Puffer als Duplikat
+    let fehlend = 150 - originale.len() as u64 - strom.rest as u64;
+    assert_eq!(strom.jitter_buffer.duplikate, fehlend);
+    assert!(fehlend < 15, "zu viele verspaetete Pakete: {fehlend}");
+    // Umsortierung treibt den gemessenen Jitter, aber nicht bis zum Maximum
+    let jitter = strom.jitter_buffer.jitter_ticks;
+    assert!((1..3 * TICKS_PRO_FRAME).contains(&jitter), "{jitter}");
+    assert!(strom.jitter_buffer.ziel_groesse <= 4);
+    assert!(
+        strom.latenz_max_ms() <= 30 + 50 + 2 * 20,
+        "{}",
+        strom.latenz_max_ms()
+    );
+}
+
+#[test]
+fn sender_flut_nach_stau_wird_vom_policer_verworfen() {
+    let mut sim = Simulation::neu(2_500).mit_policer(Duration::from_millis(200));
+    // 600 ms Stau beim Sender: 30 Pakete gehen gebuendelt raus
+    sim.sender(SenderSkript::neu(1).mit_stau(800..1_400));
+    let umschreiben = sim.empfaenger(Resequenzierung::Umschreiben);
+    let unveraendert = sim.empfaenger(Resequenzierung::Aus);
+    let ergebnis = sim.ausfuehren();
+
+    // Nur die Pakete der letzten TTL des Staus sind noch frisch genug
+    let veraltet = ergebnis.zaehler.veraltet;
+    assert!((19..=21).contains(&veraltet), "veraltet = {veraltet}");
+    assert_eq!(ergebnis.zaehler.weitergeleitet, 100 - veraltet);
+    assert_eq!(
+        ergebnis.empfaenger[umschreiben].unterdrueckt,
+        vec![(1, veraltet)]
+    );
+    assert!(ergebnis.empfaenger[unveraendert].unterdrueckt.is_empty());
+
+    // Mit Umschreiben sieht der Empfaenger keine Luecke, sonst als Verlust
+    let strom = ergebnis.strom(umschreiben, 1);
+    assert_eq!(strom.jitter_buffer.verloren, 0);
+    assert_eq!(strom.plc.gesamt_verloren, 0);
+    assert_eq!(strom.spruenge(), 0);
+    let strom = ergebnis.strom(unveraendert, 1);
+    assert_eq!(strom.jitter_buffer.verloren, veraltet);
+    assert_aufsteigend(&strom.originale());
+}
+
+#[test]
+fn notfall_stummschaltung_mitten_im_strom() {
+    let mut sim = Simulation::neu(3_500);
+    sim.sender(SenderSkript::neu(1).mit_laufzeit(0..3_000));
+    let hoerer = sim.empfaenger(Resequenzierung::Umschreiben);
+    sim.aktion(1_000, Aktion::NotfallAn);
+    sim.aktion(2_000, Aktion::NotfallAus);
+    let ergebnis = sim.ausfuehren();
+
+    // Pakete mit Ankunft in [1000, 2000) ms: gesendet ab 970 ms
+    assert_eq!(ergebnis.zaehler.notfall, 50);
+    assert_eq!(ergebnis.zaehler.weitergeleitet, 100);
+    assert_eq!(ergebnis.empfaenger[hoerer].unterdrueckt, vec![(1, 50)]);
+
+    let strom = ergebnis.strom(hoerer, 1);
+    let originale = strom.originale();
+    assert!(originale.iter().all(|s| !(49..99).contains(s)));
+    assert_eq!(originale.len() + strom.rest, 100);
+    // Umgeschrieben: der Empfaenger sieht weder Verlust noch Sprung
+    assert_eq!(strom.jitter_buffer.verloren, 0);
+    assert_eq!(strom.plc.gesamt_verloren, 0);
+    assert_eq!(strom.spruenge(), 0);
+    assert!(
+        strom.unterlaeufe >= 45,
+        "Stille waehrend der Stummschaltung"
+    );
+}
+
+#[test]
+fn pause_und_fortsetzen_synchronisiert_neu() {
+    let mut sim = Simulation::neu(5_500).mit_policer(Duration::from_millis(200));
+    sim.sender(
+        SenderSkript::neu(1)
+            .mit_laufzeit(0..5_000)
+            .mit_pause(1_000..3_000),
+    );
+    let hoerer = sim.empfaenger(Resequenzierung::Umschreiben);
+    let ergebnis = sim.ausfuehren();
+
+    // Zeitstempel stand waehrend der Pause: trotzdem nichts veraltet
+    assert_eq!(ergebnis.zaehler.veraltet, 0);
+    assert_eq!(ergebnis.zaehler.weitergeleitet, 150);
+
+    let strom = ergebnis.strom(hoerer, 1);
+    assert_eq!(strom.originale(), (0..149).collect::<Vec<_>>());
+    assert_eq!(strom.spruenge(), 0);
+    assert_eq!(strom.plc.gesamt_verloren, 0);
+    // Nach dem Fortsetzen bleibt die Latenz im Rahmen (der letzte Frame vor
+    // der Pause lag im Puffer und spielt erst danach)
+    assert!(
+        strom.latenz_max_ms_ab(50) <= 30 + 3 * 20,
+        "{}",
+        strom.latenz_max_ms_ab(50)
+    );
+}
+
+/// 50 pps mit 5 % Verlust und bis zu 80 ms Jitter neben einem sauberen Sender
+fn mischbetrieb() -> (Simulation, usize) {
+    let mut sim = Simulation::neu(10_500);
+    sim.sender(
+        SenderSkript::neu(1)
+            .mit_laufzeit(0..10_000)
+            .mit_verlust(Verlust::Rate(0.05))
+            .mit_jitter(Jitter::Gleichverteilt { max_ms: 80 }),
+    );
+    sim.sender(SenderSkript::neu(2).mit_laufzeit(0..10_000));
+    let hoerer = sim.empfaenger(Resequenzierung::Umschreiben);
+    (sim, hoerer)
+}
+
+#[test]
+fn mischbetrieb_mit_verlust_und_jitter() {
+    let (sim, hoerer) = mischbetrieb();
+    let ergebnis = sim.ausfuehren();
+
+    let gestoert = ergebnis.strom(hoerer, 1);
+    let rate = gestoert.jitter_buffer.verloren as f64 / 500.0;
+    assert!((0.03..0.15).contains(&rate), "Verlust-Rate {rate}");
+    assert_aufsteigend(&gestoert.originale());
+    assert!(gestoert.latenz_mittel_ms() < 30.0 + 80.0 + 60.0);
+
+    // Der ungestoerte Sender im selben Kanal bleibt unberuehrt
+    let sauber = ergebnis.strom(hoerer, 2);
+    assert_eq!(sauber.originale().len() + sauber.rest, 500);
+    assert_eq!(sauber.spruenge(), 0);
+
+    // Fester Startwert: ein zweiter Lauf liefert dieselben Zahlen
+    let (sim, hoerer) = mischbetrieb();
+    let wiederholt = sim.ausfuehren();
+    let wiederholt = wiederholt.strom(hoerer, 1);
+    assert_eq!(wiederholt.originale(), gestoert.originale());
+    assert_eq!(
+        wiederholt.jitter_buffer.verloren,
+        gestoert.jitter_buffer.verloren
+    );
+}
@@ -0,0 +1,328 @@
+//! Eingebaute Alarmierung fuer Betreiber
+//!
+//! Tastet periodisch interne Messwerte ab (Voice-Paketverlust,
+//! Datenbank-Erreichbarkeit, Speicherbelegung, Client-Auslastung) und wertet
+//! sie mit den Regeln aus `[alarm]` aus. Meldungen gehen ueber die Zustellwege
+//! der jeweiligen Regel raus:
+//! - Commander-Ereignis (`CommanderEreignis::Alarm`)
+//! - `ServerAnnouncement` an verbundene Administratoren
+//! - Webhook: die Meldung als JSON per `POST` an `alarm.webhook_url`
+//!
+//! Jede Meldung wird zusaetzlich geloggt, auch ohne Zustellweg.
+
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use bytes::Bytes;
+use http_body_util::{BodyExt, Full};
+use hyper::{header, Method, Request, Uri};
+use hyper_util::rt::TokioIo;
+use rustls::pki_types::ServerName;
+use rustls::{ClientConfig, RootCertStore};
+use speakeasy_db::Datenbank;
+use speakeasy_observability::alarm::{
+    speicher_belegung, AlarmArt, AlarmAuswerter, AlarmMeldung, AlarmMetrik, AlarmSchwere,
+    AlarmStatus, AlarmZiel, Messwerte,
+};
+use speakeasy_observability::metrics::globale_metriken;
+use speakeasy_observability::HealthState;
+use speakeasy_protocol::control::{AnnouncementSeverity, ServerAnnouncement};
+use speakeasy_signaling::ankuendigung;
+use speakeasy_signaling::server_state::SignalingState;
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::net::TcpStream;
+use tokio::sync::Mutex;
+use tokio_rustls::TlsConnector;
+
+use crate::config::ServerConfig;
+
+/// Zeitlimit fuer die Datenbank-Pruefung einer Auswertung
+const DB_PRUEFUNG_ZEITLIMIT: Duration = Duration::from_secs(5);
+/// Zeitlimit pro Webhook-Zustellung (Verbindungsaufbau bis Antwortende)
+const WEBHOOK_ZEITLIMIT: Duration = Duration::from_secs(10);
+
+/// Zustellung von Meldungen als Commander-Ereignis
+pub type CommanderMeldenFn = Arc<dyn Fn(AlarmMeldung) + Send + Sync>;
+
+/// Quellen der Messwerte und Zustellwege
+pub struct AlarmQuellen {
+    pub db: Arc<Datenbank>,
+    pub health: HealthState,
+    pub signaling: Arc<SignalingState<Datenbank, Datenbank, Datenbank>>,
+    pub commander: CommanderMeldenFn,
+    /// Verzeichnis des Datei-Speichers (bestimmt den Datentraeger)
+    pub speicher: PathBuf,
+}
+
+/// Auswerter samt Stand der Voice-Zaehler seit der letzten Abtastung
+struct Auswertung {
+    auswerter: AlarmAuswerter,
+    /// (empfangen, verloren) bei der letzten Abtastung
+    voice_stand: (u64, u64),
+}
+
+/// Periodische Auswertung der Alarmregeln
+pub struct Alarmierung {
+    quellen: AlarmQuellen,
+    webhook: Option<Webhook>,
+    auswertung: Mutex<Auswertung>,
+}
+
+impl Alarmierung {
+    /// Erstellt die Alarmierung aus der Konfiguration
+    ///
+    /// Eine ungueltige Webhook-URL wird geloggt; Regeln mit Webhook-Ziel
+    /// melden dann nur ueber die uebrigen Wege.
+    pub fn neu(config: &ServerConfig, quellen: AlarmQuellen) -> Self {
+        let webhook = config
+            .alarm
+            .webhook_url
+            .as_deref()
+            .and_then(|url| match Webhook::neu(url) {
+                Ok(webhook) => Some(webhook),
+                Err(e) => {
+                    tracing::warn!(url, fehler = %e, "Webhook-URL der Alarmierung ungueltig");
+                    None
+                }
+            });
+        Self {
+            quellen,
+            webhook,
+            auswertung: Mutex::new(Auswertung {
+                auswerter: AlarmAuswerter::neu(config.alarm.regeln.clone(), config.flap_grenzen()),
+                voice_stand: globale_metriken().voice_pakete_summe(),
+            }),
+        }
+    }
+
+    /// Startet die periodische Auswertung
+    pub fn starten(self: &Arc<Self>, intervall: Duration) -> tokio::task::JoinHandle<()> {
+        let alarmierung = Arc::clone(self);
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(intervall);
+            ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            loop {
+                ticker.tick().await;
+                alarmierung.pruefen().await;
+            }
+        })
+    }
+
+    /// Wertet alle Regeln sofort aus, stellt Meldungen zu und liefert den
+    /// aktuellen Zustand jeder Regel
+    pub async fn pruefen(&self) -> Vec<AlarmStatus> {
+        let (meldungen, status) = {
+            let mut auswertung = self.auswertung.lock().await;
+            let messwerte = self.messwerte(&mut auswertung.voice_stand).await;
+            let jetzt = Instant::now();
+            let meldungen = auswertung.auswerter.auswerten(&messwerte, jetzt);
+            (meldungen, auswertung.auswerter.status(jetzt))
+        };
+        for meldung in meldungen {
+            self.zustellen(meldung).await;
+        }
+        status
+    }
+
+    /// Tastet alle Messwerte ab; nicht verfuegbare Quellen bleiben leer
+    async fn messwerte(&self, voice_stand: &mut (u64, u64)) -> Messwerte {
+        let mut messwerte = Messwerte::neu();
+
+        // Paketverlust seit der letzten Abtastung; ohne Verkehr kein Wert
+        let (empfangen, verloren) = globale_metriken().voice_pakete_summe();
+        let neu_empfangen = empfangen.saturating_sub(voice_stand.0);
+        let neu_verloren = verloren.saturating_sub(voice_stand.1);
+        *voice_stand = (empfangen, verloren);
+        let erwartet = neu_empfangen + neu_verloren;
+        if erwartet > 0 {
+            messwerte.setzen(
+                AlarmMetrik::VoicePaketverlust,
+                neu_verloren as f64 * 100.0 / erwartet as f64,
+            );
+        }
+
+        let erreichbar = tokio::time::timeout(DB_PRUEFUNG_ZEITLIMIT, self.quellen.db.erreichbar())
+            .await
+            .unwrap_or(false);
+        self.quellen.health.db_status_setzen(erreichbar);
+        messwerte.setzen(
+            AlarmMetrik::DatenbankErreichbar,
+            if erreichbar { 1.0 } else { 0.0 },
+        );
+
+        // statvfs kann auf haengenden Netzlaufwerken blockieren
+        let speicher = self.quellen.speicher.clone();
+        if let Ok(Some(belegung)) =
+            tokio::task::spawn_blocking(move || speicher_belegung(&speicher)).await
+        {
+            messwerte.setzen(AlarmMetrik::SpeicherBelegung, belegung);
+        }
+
+        let signaling = &self.quellen.signaling;
+        let max_clients = signaling.einstellungen.aktuell().max_clients;
+        if max_clients > 0 {
+            let online = signaling.presence.online_anzahl();
+            messwerte.setzen(
+                AlarmMetrik::ClientAuslastung,
+                online as f64 * 100.0 / max_clients as f64,
+            );
+        }
+
+        messwerte
+    }
+
+    /// Stellt eine Meldung ueber die Zustellwege ihrer Regel zu
+    async fn zustellen(&self, meldung: AlarmMeldung) {
+        match (meldung.art, meldung.schwere) {
+            (AlarmArt::Behoben, _) | (_, AlarmSchwere::Info) => {
+                tracing::info!(regel = %meldung.regel, "{}", meldung.text())
+            }
+            (_, AlarmSchwere::Warnung) => {
+                tracing::warn!(regel = %meldung.regel, "{}", meldung.text())
+            }
+            (_, AlarmSchwere::Kritisch) => {
+                tracing::error!(regel = %meldung.regel, "{}", meldung.text())
+            }
+        }
+
+        if meldung.ziele.contains(&AlarmZiel::Ankuendigung) {
+            let empfaenger = ankuendigung::an_administratoren_senden(
+                &self.quellen.signaling,
+                ankuendigung_aus(&meldung),
+            )
+            .await;
+            tracing::debug!(regel = %meldung.regel, empfaenger, "Alarm angekuendigt");
+        }
+        if meldung.ziele.contains(&AlarmZiel::Webhook) {
+            if let Some(webhook) = &self.webhook {
+                if let Err(e) = webhook.senden(&meldung).await {
+                    tracing::warn!(regel = %meldung.regel, fehler = %e, "Webhook-Zustellung fehlgeschlagen");
+                }
+            }
+        }
+        if meldung.ziele.contains(&AlarmZiel::Commander) {
+            (self.quellen.commander)(meldung);
+        }
+    }
+}
+
+/// Uebersetzt eine Meldung in eine Ankuendigung fuer Administratoren
+fn ankuendigung_aus(meldung: &AlarmMeldung) -> ServerAnnouncement {
+    ServerAnnouncement {
+        severity: match meldung.schwere {
+            AlarmSchwere::Info => AnnouncementSeverity::Info,
+            AlarmSchwere::Warnung => AnnouncementSeverity::Warning,
+            AlarmSchwere::Kritisch => AnnouncementSeverity::Critical,
+        },
+        title: meldung.regel.clone(),
+        message: meldung.text(),
+        resolved: meldung.art == AlarmArt::Behoben,
+    }
+}
+
+/// Webhook-Ziel (http oder https mit Web-PKI-Wurzeln)
+struct Webhook {
+    uri: Uri,
+    host: String,
+    port: u16,
+    tls: Option<TlsConnector>,
+}
+
+impl Webhook {
+    fn neu(url: &str) -> anyhow::Result<Self> {
+        let uri: Uri = url.parse()?;
+        let https = match uri.scheme_str() {
+            Some("https") => true,
+            Some("http") => false,
+            _ => anyhow::bail!("nur http:// und https:// werden unterstuetzt"),
+        };
+        let host = uri
+            .host()
+            .ok_or_else(|| anyhow::anyhow!("Host fehlt"))?
+            .trim_start_matches('[')
+            .trim_end_matches(']')
+            .to_string();
+        let port = uri.port_u16().unwrap_or(if https { 443 } else { 80 });
+        let tls = if https {
+            let mut wurzeln = RootCertStore::empty();
+            wurzeln.extend(webpki_roots::TLS_SERVER_ROOTS.iter().cloned());
+            let config = ClientConfig::builder_with_provider(Arc::new(
+                rustls::crypto::ring::default_provider(),
+            ))
+            .with_safe_default_protocol_versions()?
+            .with_root_certificates(wurzeln)
+            .with_no_client_auth();
+            Some(TlsConnector::from(Arc::new(config)))
+        } else {
+            None
+        };
+        Ok(Self {
+            uri,
+            host,
+            port,
+            tls,
+        })
+    }
+
+    /// Sendet die Meldung als JSON; Statuscodes ausserhalb 2xx sind Fehler
+    async fn senden(&self, meldung: &AlarmMeldung) -> anyhow::Result<()> {
+        let pfad = self
+            .uri
+            .path_and_query()
+            .map_or("/", |pfad| pfad.as_str())
+            .to_string();
+        let host = match self.uri.authority() {
+            Some(autoritaet) => autoritaet.as_str().to_string(),
+            None => self.host.clone(),
+        };
+        let anfrage = Request::builder()
+            .method(Method::POST)
+            .uri(pfad)
+            .header(header::HOST, host)
+            .header(header::CONTENT_TYPE, "application/json")
+            .header(
+                header::USER_AGENT,
+                concat!("speakeasy/", env!("CARGO_PKG_VERSION")),
+            )
+            .body(Full::new(Bytes::from(serde_json::to_vec(meldung)?)))?;
+
+        let status = tokio::time::timeout(WEBHOOK_ZEITLIMIT, async {
+            let tcp = TcpStream::connect((self.host.as_str(), self.port)).await?;
+            let _ = tcp.set_nodelay(true);
+            match &self.tls {
+                Some(tls) => {
+                    let name = ServerName::try_from(self.host.clone())?;
+                    austauschen(tls.connect(name, tcp).await?, anfrage).await
+                }
+                None => austauschen(tcp, anfrage).await,
+            }
+        })
+        .await
+        .map_err(|_| anyhow::anyhow!("Zeitlimit von {WEBHOOK_ZEITLIMIT:?} ueberschritten"))??;
+        if !status.is_success() {
+            anyhow::bail!("Webhook antwortet mit {status}");
+        }
+        Ok(())
+    }
+}
+
+/// Fuehrt genau einen Anfrage/Antwort-Austausch ueber `stream` aus
+async fn austauschen<S>(
+    stream: S,
+    anfrage: Request<Full<Bytes>>,
+) -> anyhow::Result<hyper::StatusCode>
+where
+    S: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+{
+    let (mut sender, verbindung) =
+        hyper::client::conn::http1::handshake(TokioIo::new(stream)).await?;
+    tokio::spawn(async move {
+        let _ = verbindung.await;
+    });
+    let antwort = sender.send_request(anfrage).await?;
+    let status = antwort.status();
+    // Rumpf lesen, damit der Empfaenger die Verbindung sauber beenden kann
+    antwort.into_body().collect().await?;
+    Ok(status)
+}
@@ -5,6 +5,25 @@
 //! lauffaehig ist.
 
 use serde::{Deserialize, Serialize};
+use speakeasy_chat::{DownloadKonfig, KontoKonfig, UploadKonfig, ZugriffsLogModus};
+use speakeasy_commander::auth::ZertifikatsZuordnung;
+use speakeasy_commander::tls::{ClientZertModus, TlsKonfig, STANDARD_NACHLADE_INTERVALL};
+use speakeasy_core::types::ChannelId;
+use speakeasy_db::models::NachrichtenRichtlinie;
+use speakeasy_db::repository::{DatabaseBackend, DatabaseConfig};
+use speakeasy_db::zeitlimit::Zeitlimits;
+use speakeasy_db::AuditPufferKonfig;
+use speakeasy_observability::alarm::{AlarmRegel, FlapGrenzen};
+use speakeasy_protocol::codec::AudioPreset;
+use speakeasy_signaling::afk::AfkRichtlinie;
+use speakeasy_signaling::anfragelimit::AnfrageLimits;
+use speakeasy_signaling::broadcast::ReplayKonfig;
+use speakeasy_signaling::drosselung::{DrosselLimits, EimerLimit};
+use speakeasy_signaling::einladung::EinladungsLimits;
+use speakeasy_signaling::mitschnitt::MitschnittKonfig;
+use speakeasy_signaling::server_state::RegistrierungsModus;
+use speakeasy_voice::PingLimits;
+use std::time::Duration;
 
 /// Vollstaendige Server-Konfiguration
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -13,6 +32,8 @@ use serde::{Deserialize, Serialize};
 pub struct ServerConfig {
     /// Allgemeine Server-Einstellungen
     pub server: ServerEinstellungen,
+    /// Rate-Begrenzung des Control-Kanals je Verbindung
+    pub drosselung: DrosselEinstellungen,
     /// Netzwerk-Einstellungen
     pub netzwerk: NetzwerkEinstellungen,
     /// Datenbank-Einstellungen
@@ -27,6 +48,14 @@ pub struct ServerConfig {
     pub observability: ObservabilityEinstellungen,
     /// Plugin-Einstellungen
     pub plugins: PluginEinstellungen,
+    /// Datei-Einstellungen (Speicher, Zugriffsprotokoll)
+    pub dateien: DateiEinstellungen,
+    /// Zeitplaner fuer geplante Aktionen
+    pub zeitplaner: ZeitplanerEinstellungen,
+    /// Datenexport und Loeschung von Benutzerkonten
+    pub konten: KontenEinstellungen,
+    /// Eingebaute Alarmierung fuer Betreiber
+    pub alarm: AlarmEinstellungen,
 }
 
 /// Allgemeine Server-Einstellungen
@@ -41,6 +70,57 @@ pub struct ServerEinstellungen {
     pub willkommen: Option<String>,
     /// Server-Passwort (leer = kein Passwort)
     pub passwort: Option<String>,
+    /// Maximale Anzahl an Kanaelen (0 = unbegrenzt)
+    pub max_kanaele: u32,
+    /// Maximale Verschachtelungstiefe von Kanaelen (0 = unbegrenzt)
+    pub max_kanal_tiefe: u32,
+    /// Ab dieser Kanalanzahl erhalten Clients den Kanalbaum nur teilweise
+    /// und laden Zweige bei Bedarf nach (0 = immer vollstaendig)
+    pub kanalbaum_teilweise_ab: u32,
+    /// Ab mehr Mitgliedern enthaelt die Beitrittsantwort nur die zuletzt
+    /// aktiven; den Rest laden Clients seitenweise nach (0 = immer alle)
+    pub kanal_mitglieder_teilweise_ab: u32,
+    /// Anzahl zuletzt aktiver Mitglieder in einer gekuerzten Beitrittsantwort
+    pub kanal_mitglieder_vorschau: u32,
+    /// So lange (Sekunden) duerfen Benutzer eigene Chat-Nachrichten
+    /// bearbeiten oder loeschen (0 = unbegrenzt); Kanaele koennen abweichen.
+    /// Nur Vorbelegung, danach gilt der Wert in der Datenbank.
+    pub chat_bearbeitungsfrist_sek: u32,
+    /// Inaktivitaet in Sekunden bis zur Verschiebung in den AFK-Kanal (0 = deaktiviert)
+    pub afk_timeout_sek: u32,
+    /// Ziel-Kanal fuer inaktive Clients
+    pub afk_kanal: Option<uuid::Uuid>,
+    /// Gleichzeitige Anfragen pro Verbindung (0 = unbegrenzt)
+    pub max_anfragen_pro_verbindung: u32,
+    /// Gleichzeitige Anfragen pro Benutzer ueber alle Verbindungen (0 = unbegrenzt)
+    pub max_anfragen_pro_benutzer: u32,
+    /// Serverweit gleichzeitige datenbanklastige Anfragen (0 = unbegrenzt)
+    pub max_db_anfragen: u32,
+    /// Wartezeit auf einen freien Datenbank-Platz in Millisekunden
+    pub db_anfragen_wartezeit_ms: u64,
+    /// Gespeicherte Presence-Ereignisse fuer StateDiff (0 = immer Neuladen)
+    pub replay_ring_groesse: u32,
+    /// So lange bleiben Presence-Ereignisse abrufbar (Sekunden)
+    pub replay_aufbewahrung_sek: u64,
+    /// So lange bleiben Kanal-Einladungen und Beitrittsanfragen offen (Sekunden)
+    pub einladung_gueltigkeit_sek: u64,
+    /// Offene Einladungen je Ziel bzw. Anfragen je Kanal (0 = unbegrenzt)
+    pub max_offene_einladungen: u32,
+    /// Einladungen und Anfragen je Benutzer und Minute (0 = unbegrenzt)
+    pub einladungen_pro_minute: u32,
+    /// Marker-Datei, an der ein Neustart einen Absturz des vorherigen Laufs erkennt
+    pub shutdown_marker: String,
+    /// Verzeichnis fuer Sitzungsmitschnitte (None = keine Mitschnitte)
+    pub mitschnitt_verzeichnis: Option<String>,
+    /// Jede Verbindung mitschneiden (nur in Debug-Builds wirksam)
+    pub mitschnitt_alle_verbindungen: bool,
+    /// Registrierung ueber den Client: "open", "invite_only" oder "closed"
+    pub registrierung: RegistrierungsModus,
+    /// `Login` nur nach vorherigem `Hello` annehmen (false = aeltere Clients zulassen)
+    pub hello_erforderlich: bool,
+    /// Restlaufzeit der Session in Sekunden, ab der Clients zum Verlaengern
+    /// aufgefordert werden (0 = keine Warnung)
+    pub sitzungswarnung_sek: u64,
 }
 
 impl Default for ServerEinstellungen {
@@ -50,6 +130,81 @@ impl Default for ServerEinstellungen {
             max_clients: 512,
             willkommen: None,
             passwort: None,
+            max_kanaele: 1000,
+            max_kanal_tiefe: 8,
+            kanalbaum_teilweise_ab: 500,
+            kanal_mitglieder_teilweise_ab: 250,
+            kanal_mitglieder_vorschau: 50,
+            chat_bearbeitungsfrist_sek: 0,
+            afk_timeout_sek: 0,
+            afk_kanal: None,
+            max_anfragen_pro_verbindung: 8,
+            max_anfragen_pro_benutzer: 16,
+            max_db_anfragen: 32,
+            db_anfragen_wartezeit_ms: 500,
+            replay_ring_groesse: 1024,
+            replay_aufbewahrung_sek: 300,
+            einladung_gueltigkeit_sek: 120,
+            max_offene_einladungen: 5,
+            einladungen_pro_minute: 10,
+            shutdown_marker: "data/shutdown.marker".into(),
+            mitschnitt_verzeichnis: None,
+            mitschnitt_alle_verbindungen: false,
+            registrierung: RegistrierungsModus::default(),
+            hello_erforderlich: true,
+            sitzungswarnung_sek: 600,
+        }
+    }
+}
+
+/// Rate-Begrenzung des Control-Kanals je Verbindung
+///
+/// Je Kategorie laufen `*_pro_minute` Token nach (0 = unbegrenzt), am Stueck
+/// sind bis zu `*_stoss` Nachrichten erlaubt.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DrosselEinstellungen {
+    /// Hello, Login und Passwortaenderung
+    pub anmeldung_pro_minute: u32,
+    pub anmeldung_stoss: u32,
+    /// Chat senden, bearbeiten, loeschen und Verlauf
+    pub chat_pro_minute: u32,
+    pub chat_stoss: u32,
+    /// Kanaele auflisten, betreten, anlegen, aendern und loeschen
+    pub kanal_pro_minute: u32,
+    pub kanal_stoss: u32,
+    /// Pings des Clients
+    pub ping_pro_minute: u32,
+    pub ping_stoss: u32,
+    /// Alle uebrigen Anfragen
+    pub sonstige_pro_minute: u32,
+    pub sonstige_stoss: u32,
+    /// So viele Ablehnungen innerhalb von `fenster_sek` trennen die
+    /// Verbindung (0 = nie trennen)
+    pub trennen_ab: u32,
+    /// Zeitfenster fuer `trennen_ab` in Sekunden
+    pub fenster_sek: u64,
+    /// Getrennte IPs so lange automatisch bannen (Sekunden, 0 = kein Ban)
+    pub ip_sperre_sek: u64,
+}
+
+impl Default for DrosselEinstellungen {
+    fn default() -> Self {
+        let limits = DrosselLimits::default();
+        Self {
+            anmeldung_pro_minute: limits.anmeldung.pro_minute,
+            anmeldung_stoss: limits.anmeldung.stoss,
+            chat_pro_minute: limits.chat.pro_minute,
+            chat_stoss: limits.chat.stoss,
+            kanal_pro_minute: limits.kanal.pro_minute,
+            kanal_stoss: limits.kanal.stoss,
+            ping_pro_minute: limits.ping.pro_minute,
+            ping_stoss: limits.ping.stoss,
+            sonstige_pro_minute: limits.sonstige.pro_minute,
+            sonstige_stoss: limits.sonstige.stoss,
+            trennen_ab: limits.trennen_ab as u32,
+            fenster_sek: limits.fenster.as_secs(),
+            ip_sperre_sek: 0,
         }
     }
 }
@@ -72,6 +227,18 @@ pub struct NetzwerkEinstellungen {
     pub tls_zertifikat: Option<String>,
     /// TLS-Schluessel-Pfad
     pub tls_schluessel: Option<String>,
+    /// DSCP-Wert fuer ausgehende Voice-Pakete (46 = EF, 0 = keine Markierung)
+    pub voice_dscp: u8,
+    /// Empfangspuffer des Voice-Sockets in Bytes (leer = Systemstandard)
+    pub voice_empfangspuffer_bytes: Option<usize>,
+    /// Sendepuffer des Voice-Sockets in Bytes (leer = Systemstandard)
+    pub voice_sendepuffer_bytes: Option<usize>,
+    /// Beantwortete Pings pro Sekunde und Quell-IP (0 = Pings ignorieren)
+    pub voice_ping_pro_sek: f64,
+    /// Pings, die eine Quell-IP auf einmal beantwortet bekommt
+    pub voice_ping_burst: u32,
+    /// Groesste angenommene Control-Nachricht in Bytes
+    pub max_frame_bytes: usize,
 }
 
 impl Default for NetzwerkEinstellungen {
@@ -84,6 +251,12 @@ impl Default for NetzwerkEinstellungen {
             grpc_port: 10443,
             tls_zertifikat: None,
             tls_schluessel: None,
+            voice_dscp: 46,
+            voice_empfangspuffer_bytes: None,
+            voice_sendepuffer_bytes: None,
+            voice_ping_pro_sek: 5.0,
+            voice_ping_burst: 10,
+            max_frame_bytes: 1024 * 1024,
         }
     }
 }
@@ -98,6 +271,16 @@ pub struct DatenbankEinstellungen {
     pub url: String,
     /// Maximale Verbindungspool-Groesse
     pub max_verbindungen: u32,
+    /// Zeitlimit fuer lesende Anfragen in Millisekunden (Commander + Signaling)
+    pub timeout_lesen_ms: u64,
+    /// Zeitlimit fuer schreibende Anfragen in Millisekunden (Commander + Signaling)
+    pub timeout_schreiben_ms: u64,
+    /// Maximale Anzahl ungeschriebener Audit-Ereignisse
+    pub audit_queue: usize,
+    /// Audit-Ereignisse pro Schreibvorgang
+    pub audit_batch_groesse: usize,
+    /// Hoechste Wartezeit eines Audit-Ereignisses in Millisekunden
+    pub audit_intervall_ms: u64,
 }
 
 impl Default for DatenbankEinstellungen {
@@ -106,6 +289,11 @@ impl Default for DatenbankEinstellungen {
             typ: "sqlite".into(),
             url: "sqlite://speakeasy.db".into(),
             max_verbindungen: 5,
+            timeout_lesen_ms: 2000,
+            timeout_schreiben_ms: 5000,
+            audit_queue: 4096,
+            audit_batch_groesse: 50,
+            audit_intervall_ms: 250,
         }
     }
 }
@@ -116,18 +304,32 @@ impl Default for DatenbankEinstellungen {
 pub struct AudioEinstellungen {
     /// Maximale Bitrate pro Client in kbit/s
     pub max_bitrate_kbps: u32,
+    /// Opus-Presets, die Clients aushandeln duerfen (leer = alle)
+    pub erlaubte_presets: Vec<AudioPreset>,
     /// Jitter-Buffer-Groesse in Millisekunden
     pub jitter_buffer_ms: u32,
     /// Maximale Stille-Erkennungszeit in ms bevor Client gemuted wird
     pub stille_timeout_ms: u32,
+    /// Clients ohne funktionierendes Opus duerfen auf PCMU (G.711) ausweichen
+    pub pcm_fallback_erlaubt: bool,
+    /// Voice-Pakete, die um mehr als diese Zeit hinter dem Takt des Absenders
+    /// liegen, werden nicht weitergeleitet (leer = alles weiterleiten)
+    pub paket_ttl_ms: Option<u32>,
+    /// Bei Clients mit Resequenzierung entfallen serverseitige Pausen ab
+    /// dieser Laenge auch im Zeitstempel (leer = Zeitstempel nie umschreiben)
+    pub zeitstempel_glaetten_ab_ms: Option<u32>,
 }
 
 impl Default for AudioEinstellungen {
     fn default() -> Self {
         Self {
             max_bitrate_kbps: 128,
+            erlaubte_presets: Vec::new(),
             jitter_buffer_ms: 60,
             stille_timeout_ms: 300,
+            pcm_fallback_erlaubt: false,
+            paket_ttl_ms: None,
+            zeitstempel_glaetten_ab_ms: None,
         }
     }
 }
@@ -160,25 +362,118 @@ impl Default for LoggingEinstellungen {
 pub struct CommanderEinstellungen {
     /// Port fuer die REST-API (Standard: 8080)
     pub rest_port: u16,
+    /// TCP-ServerQuery starten (Standard: false)
+    pub tcp_aktiviert: bool,
     /// Port fuer TCP/TLS ServerQuery (Standard: 10011)
     pub tcp_port: u16,
     /// Maximale TCP-Verbindungen
     pub tcp_max_verbindungen: usize,
+    /// TLS fuer ServerQuery (fehlt = unverschluesselt)
+    pub tcp_tls: Option<CommanderTcpTlsEinstellungen>,
     /// CORS-Origins fuer REST (leer = alle erlaubt)
     pub cors_origins: Vec<String>,
+    /// TLS fuer REST und gRPC (fehlt = unverschluesselt)
+    pub tls: Option<CommanderTlsEinstellungen>,
+    /// Sekunden, die REST, gRPC und ServerQuery beim Herunterfahren fuer
+    /// laufende Anfragen bekommen, bevor sie abgebrochen werden (Standard: 10)
+    pub shutdown_zeitlimit_sek: u64,
 }
 
 impl Default for CommanderEinstellungen {
     fn default() -> Self {
         Self {
             rest_port: 8080,
+            tcp_aktiviert: false,
             tcp_port: 10011,
             tcp_max_verbindungen: 100,
+            tcp_tls: None,
             cors_origins: vec![],
+            tls: None,
+            shutdown_zeitlimit_sek: 10,
         }
     }
 }
 
+/// TLS fuer den TCP-ServerQuery-Zugang
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CommanderTcpTlsEinstellungen {
+    /// Server-Zertifikatskette (PEM)
+    pub zertifikat: String,
+    /// Privater Schluessel (PEM)
+    pub schluessel: String,
+}
+
+impl CommanderTcpTlsEinstellungen {
+    /// TLS-Konfiguration fuer den TCP-Server
+    pub fn tls_konfig(&self) -> TlsKonfig {
+        TlsKonfig::neu(&self.zertifikat, &self.schluessel)
+    }
+}
+
+/// Wirkung eines zugeordneten Client-Zertifikats
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ZertifikatsAuthModus {
+    /// Zertifikat sichert nur die Verbindung, der Bearer-Token bleibt Pflicht
+    #[default]
+    Ergaenzend,
+    /// Ein zugeordnetes Zertifikat ersetzt den Bearer-Token
+    Ersetzend,
+}
+
+/// TLS- und mTLS-Einstellungen fuer REST und gRPC des Commanders
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CommanderTlsEinstellungen {
+    /// Server-Zertifikatskette (PEM)
+    pub zertifikat: String,
+    /// Privater Schluessel (PEM)
+    pub schluessel: String,
+    /// CA-Bundle fuer Client-Zertifikate (fehlt = kein mTLS)
+    pub client_ca: Option<String>,
+    /// "pflicht" oder "optional"
+    pub client_zertifikate: ClientZertModus,
+    /// "ergaenzend" oder "ersetzend"
+    pub zertifikats_auth: ZertifikatsAuthModus,
+    /// Zuordnung von Zertifikatsnamen zu Benutzern und Scopes
+    pub zuordnungen: Vec<ZertifikatsZuordnung>,
+    /// Abstand der Pruefung auf erneuerte Zertifikate in Sekunden (Standard: 60)
+    pub nachlade_intervall_sek: u64,
+}
+
+impl Default for CommanderTlsEinstellungen {
+    fn default() -> Self {
+        Self {
+            zertifikat: String::new(),
+            schluessel: String::new(),
+            client_ca: None,
+            client_zertifikate: ClientZertModus::default(),
+            zertifikats_auth: ZertifikatsAuthModus::default(),
+            zuordnungen: vec![],
+            nachlade_intervall_sek: STANDARD_NACHLADE_INTERVALL.as_secs(),
+        }
+    }
+}
+
+impl CommanderTlsEinstellungen {
+    /// TLS-Konfiguration fuer REST- und gRPC-Server
+    pub fn tls_konfig(&self) -> TlsKonfig {
+        let mut konfig = TlsKonfig::neu(&self.zertifikat, &self.schluessel);
+        if let Some(client_ca) = &self.client_ca {
+            konfig = konfig.mit_client_ca(client_ca, self.client_zertifikate);
+        }
+        konfig.nachlade_intervall = Duration::from_secs(self.nachlade_intervall_sek.max(1));
+        konfig
+    }
+
+    /// Zuordnungen, falls Zertifikate den Bearer-Token ersetzen duerfen
+    pub fn ersetzende_zuordnungen(&self) -> Option<&[ZertifikatsZuordnung]> {
+        (self.client_ca.is_some() && self.zertifikats_auth == ZertifikatsAuthModus::Ersetzend)
+            .then_some(self.zuordnungen.as_slice())
+    }
+}
+
 /// Observability-Einstellungen (Metriken + Health-Check)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(default)]
@@ -187,6 +482,9 @@ pub struct ObservabilityEinstellungen {
     pub aktiviert: bool,
     /// Port fuer Metriken und Health (Standard: 9300)
     pub port: u16,
+    /// Sekunden, die der Server nach einem Startfehler noch den Fehlergrund
+    /// auf `/health/ready` meldet, bevor er sich beendet (Standard: 30)
+    pub start_fehler_nachlauf_sek: u64,
 }
 
 impl Default for ObservabilityEinstellungen {
@@ -194,6 +492,7 @@ impl Default for ObservabilityEinstellungen {
         Self {
             aktiviert: true,
             port: 9300,
+            start_fehler_nachlauf_sek: 30,
         }
     }
 }
@@ -208,6 +507,120 @@ pub struct PluginEinstellungen {
     pub verzeichnis: Option<String>,
 }
 
+/// Datei-Einstellungen
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DateiEinstellungen {
+    /// Verzeichnis fuer hochgeladene Dateien
+    pub speicher_verzeichnis: String,
+    /// Download-Zugriffsprotokoll: "deaktiviert", "aggregiert" oder "vollstaendig"
+    pub zugriffs_log: ZugriffsLogModus,
+    /// Aufbewahrungsdauer des Zugriffsprotokolls in Tagen (0 = unbegrenzt)
+    pub zugriffs_log_aufbewahrung_tage: u32,
+    /// Maximale Anzahl ungeschriebener Protokolleintraege
+    pub zugriffs_log_queue: usize,
+    /// Port fuer Downloads per Einmal-Link (Standard: 9301)
+    pub http_port: u16,
+    /// Minuten, die ein Download-Link fuer Dateianhaenge gilt (Standard: 10)
+    pub download_gueltigkeit_min: u32,
+    /// Minuten, die ein Upload-Link nach der Anmeldung gilt (Standard: 10)
+    pub upload_gueltigkeit_min: u32,
+    /// Von aussen erreichbare Basis-URL des Datei-Servers, z.B. hinter einem
+    /// Reverse-Proxy (fehlt = `http://<bind_adresse>:<http_port>`)
+    pub oeffentliche_url: Option<String>,
+}
+
+impl Default for DateiEinstellungen {
+    fn default() -> Self {
+        Self {
+            speicher_verzeichnis: "data/files".into(),
+            zugriffs_log: ZugriffsLogModus::Vollstaendig,
+            zugriffs_log_aufbewahrung_tage: 90,
+            zugriffs_log_queue: 1024,
+            http_port: 9301,
+            download_gueltigkeit_min: 10,
+            upload_gueltigkeit_min: 10,
+            oeffentliche_url: None,
+        }
+    }
+}
+
+/// Einstellungen fuer Datenexport und Kontoloeschung
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct KontenEinstellungen {
+    /// Nachrichten geloeschter Konten: "behalten" (nur Absender ersetzen)
+    /// oder "leeren" (Inhalt entfernen)
+    pub nachrichten_bei_loeschung: NachrichtenRichtlinie,
+    /// Stunden, die ein Export-Archiv abgeholt werden kann (Standard: 24)
+    pub export_gueltigkeit_std: u32,
+    /// Mindestabstand zwischen zwei selbst angeforderten Exporten in Stunden
+    /// (Standard: 24)
+    pub export_sperrfrist_std: u32,
+}
+
+impl Default for KontenEinstellungen {
+    fn default() -> Self {
+        Self {
+            nachrichten_bei_loeschung: NachrichtenRichtlinie::Behalten,
+            export_gueltigkeit_std: 24,
+            export_sperrfrist_std: 24,
+        }
+    }
+}
+
+/// Einstellungen des Zeitplaners (geplante Aktionen)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ZeitplanerEinstellungen {
+    /// Aktiviert die Ausfuehrung geplanter Aktionen
+    pub aktiviert: bool,
+    /// Verpasste Termine, die aelter als diese Anzahl Minuten sind, werden
+    /// uebersprungen statt nachgeholt (Standard: 60)
+    pub verfallszeit_min: u32,
+}
+
+impl Default for ZeitplanerEinstellungen {
+    fn default() -> Self {
+        Self {
+            aktiviert: true,
+            verfallszeit_min: 60,
+        }
+    }
+}
+
+/// Einstellungen der eingebauten Alarmierung
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AlarmEinstellungen {
+    /// Aktiviert die periodische Auswertung der Alarmregeln
+    pub aktiviert: bool,
+    /// Abstand zwischen zwei Auswertungen in Sekunden (Standard: 30)
+    pub intervall_sek: u64,
+    /// Ziel-URL fuer Webhook-Alarme (JSON per POST, `None` = keine Webhooks)
+    pub webhook_url: Option<String>,
+    /// Beobachtungsfenster der Flap-Erkennung in Minuten (Standard: 30)
+    pub flap_fenster_min: u64,
+    /// Zustandswechsel im Fenster, ab denen eine Regel als flatternd gilt
+    /// (0 = Flap-Erkennung aus)
+    pub flap_max_wechsel: usize,
+    /// Alarmregeln (Standard: [`AlarmRegel::standard`])
+    pub regeln: Vec<AlarmRegel>,
+}
+
+impl Default for AlarmEinstellungen {
+    fn default() -> Self {
+        Self {
+            aktiviert: true,
+            intervall_sek: 30,
+            webhook_url: None,
+            flap_fenster_min: 30,
+            flap_max_wechsel: 4,
+            regeln: AlarmRegel::standard(),
+        }
+    }
+}
+
 impl ServerConfig {
     /// Laedt die Konfiguration aus einer TOML-Datei.
     /// Gibt die Standardkonfiguration zurueck wenn die Datei nicht existiert.
@@ -231,6 +644,51 @@ impl ServerConfig {
         }
     }
 
+    /// Warnungen fuer Abschnitte, deren Subsystem nicht einkompiliert ist
+    ///
+    /// `features` sind die Cargo-Features des Binaries. Der Server startet
+    /// trotzdem und laesst die betroffenen Einstellungen unbeachtet.
+    pub fn feature_warnungen(&self, features: &[&str]) -> Vec<String> {
+        let fehlt = |feature: &str| !features.contains(&feature);
+        let mut warnungen = Vec::new();
+        let mut melden = |abschnitt: &str, feature: &str| {
+            warnungen.push(format!(
+                "{abschnitt} ist konfiguriert, aber dieser Server wurde ohne \
+                 Feature `{feature}` gebaut und ignoriert die Einstellung"
+            ));
+        };
+        if self.plugins.aktiviert && fehlt("plugins") {
+            melden("[plugins] aktiviert", "plugins");
+        }
+        if self.netzwerk.grpc_port != NetzwerkEinstellungen::default().grpc_port && fehlt("grpc") {
+            melden("[netzwerk] grpc_port", "grpc");
+        }
+        if self.observability.aktiviert && fehlt("observability") {
+            melden("[observability] aktiviert", "observability");
+        }
+        if self.alarm.aktiviert && fehlt("observability") {
+            melden("[alarm] aktiviert", "observability");
+        }
+        if self.commander.tcp_aktiviert && fehlt("tcp-commander") {
+            melden("[commander] tcp_aktiviert", "tcp-commander");
+        }
+        warnungen
+    }
+
+    /// Kurze Pruefsumme der wirksamen Konfiguration (SHA-256, 16 Hex-Zeichen)
+    ///
+    /// Landet im Startprotokoll, damit sich Neustarts mit geaenderter
+    /// Konfiguration erkennen lassen, ohne Werte preiszugeben.
+    pub fn pruefsumme(&self) -> String {
+        use sha2::{Digest, Sha256};
+        let serialisiert = toml::to_string(self).unwrap_or_default();
+        Sha256::digest(serialisiert.as_bytes())
+            .iter()
+            .take(8)
+            .map(|b| format!("{b:02x}"))
+            .collect()
+    }
+
     /// Gibt die vollstaendige Bind-Adresse fuer TCP zurueck
     pub fn tcp_bind_adresse(&self) -> String {
         format!("{}:{}", self.netzwerk.bind_adresse, self.netzwerk.tcp_port)
@@ -241,6 +699,126 @@ impl ServerConfig {
         format!("{}:{}", self.netzwerk.bind_adresse, self.netzwerk.udp_port)
     }
 
+    /// Gibt die DSCP-Markierung fuer den Voice-Server zurueck (`None` = deaktiviert)
+    pub fn voice_dscp(&self) -> Option<u8> {
+        Some(self.netzwerk.voice_dscp).filter(|&d| d != 0)
+    }
+
+    /// Gibt die Grenzen des Ping-Reflektors zurueck (`None` = abgeschaltet)
+    pub fn voice_ping(&self) -> Option<PingLimits> {
+        let netzwerk = &self.netzwerk;
+        if netzwerk.voice_ping_pro_sek <= 0.0 || netzwerk.voice_ping_burst == 0 {
+            return None;
+        }
+        Some(PingLimits {
+            pro_sekunde: netzwerk.voice_ping_pro_sek,
+            burst: netzwerk.voice_ping_burst,
+            ..PingLimits::default()
+        })
+    }
+
+    /// Gibt die Verbindungskonfiguration fuer das gewaehlte Backend zurueck
+    ///
+    /// `typ` ist "sqlite" oder "postgres" (auch "postgresql"); andere Werte
+    /// sind ein Fehler statt eines stillen Rueckfalls auf SQLite.
+    pub fn datenbank_config(&self) -> anyhow::Result<DatabaseConfig> {
+        let backend = match self.datenbank.typ.trim().to_ascii_lowercase().as_str() {
+            "sqlite" => DatabaseBackend::Sqlite,
+            "postgres" | "postgresql" => DatabaseBackend::Postgres,
+            anderes => anyhow::bail!(
+                "Unbekannter Datenbank-Typ '{anderes}' (erlaubt: \"sqlite\", \"postgres\")"
+            ),
+        };
+        Ok(DatabaseConfig {
+            sqlite_wal: backend == DatabaseBackend::Sqlite,
+            backend,
+            url: self.datenbank.url.clone(),
+            max_verbindungen: self.datenbank.max_verbindungen,
+        })
+    }
+
+    /// Gibt die Zeitlimits fuer Datenbank-Anfragen zurueck
+    pub fn zeitlimits(&self) -> Zeitlimits {
+        Zeitlimits {
+            lesen: Duration::from_millis(self.datenbank.timeout_lesen_ms),
+            schreiben: Duration::from_millis(self.datenbank.timeout_schreiben_ms),
+        }
+    }
+
+    /// Gibt die Konfiguration des gepufferten Audit-Logs zurueck
+    pub fn audit_puffer(&self) -> AuditPufferKonfig {
+        AuditPufferKonfig {
+            queue_groesse: self.datenbank.audit_queue,
+            batch_groesse: self.datenbank.audit_batch_groesse,
+            intervall: Duration::from_millis(self.datenbank.audit_intervall_ms),
+        }
+    }
+
+    /// Gibt die Grenzen fuer gleichzeitige Signaling-Anfragen zurueck
+    pub fn anfrage_limits(&self) -> AnfrageLimits {
+        AnfrageLimits {
+            pro_verbindung: self.server.max_anfragen_pro_verbindung as usize,
+            pro_benutzer: self.server.max_anfragen_pro_benutzer as usize,
+            db_gleichzeitig: self.server.max_db_anfragen as usize,
+            db_wartezeit: Duration::from_millis(self.server.db_anfragen_wartezeit_ms),
+            ..Default::default()
+        }
+    }
+
+    /// Gibt die Rate-Begrenzung fuer Signaling-Verbindungen zurueck
+    pub fn drossel_limits(&self) -> DrosselLimits {
+        let d = &self.drosselung;
+        DrosselLimits {
+            anmeldung: EimerLimit::neu(d.anmeldung_pro_minute, d.anmeldung_stoss),
+            chat: EimerLimit::neu(d.chat_pro_minute, d.chat_stoss),
+            kanal: EimerLimit::neu(d.kanal_pro_minute, d.kanal_stoss),
+            ping: EimerLimit::neu(d.ping_pro_minute, d.ping_stoss),
+            sonstige: EimerLimit::neu(d.sonstige_pro_minute, d.sonstige_stoss),
+            trennen_ab: d.trennen_ab as usize,
+            fenster: Duration::from_secs(d.fenster_sek),
+            ip_sperre: (d.ip_sperre_sek > 0).then(|| Duration::from_secs(d.ip_sperre_sek)),
+        }
+    }
+
+    /// Gibt die Grenzen des Replay-Rings fuer Presence-Ereignisse zurueck
+    pub fn replay_konfig(&self) -> ReplayKonfig {
+        ReplayKonfig {
+            groesse: self.server.replay_ring_groesse as usize,
+            aufbewahrung: Duration::from_secs(self.server.replay_aufbewahrung_sek),
+        }
+    }
+
+    /// Gibt die Einstellungen fuer Sitzungsmitschnitte zurueck
+    pub fn mitschnitt_konfig(&self) -> MitschnittKonfig {
+        MitschnittKonfig {
+            verzeichnis: self
+                .server
+                .mitschnitt_verzeichnis
+                .as_ref()
+                .filter(|pfad| !pfad.is_empty())
+                .map(Into::into),
+            alle_verbindungen: self.server.mitschnitt_alle_verbindungen,
+        }
+    }
+
+    /// Gibt die Grenzen fuer Kanal-Einladungen und Beitrittsanfragen zurueck
+    pub fn einladungs_limits(&self) -> EinladungsLimits {
+        EinladungsLimits {
+            gueltigkeit: Duration::from_secs(self.server.einladung_gueltigkeit_sek.max(1)),
+            offen_pro_ziel: self.server.max_offene_einladungen as usize,
+            pro_benutzer: self.server.einladungen_pro_minute as usize,
+            fenster: Duration::from_secs(60),
+        }
+    }
+
+    /// Gibt die AFK-Richtlinie fuer den Signaling-Server zurueck
+    pub fn afk_richtlinie(&self) -> AfkRichtlinie {
+        AfkRichtlinie {
+            schwelle: Duration::from_secs(self.server.afk_timeout_sek as u64),
+            afk_kanal: self.server.afk_kanal.map(ChannelId),
+        }
+    }
+
     /// Gibt die Bind-Adresse fuer den Commander REST-Server zurueck
     pub fn commander_rest_bind_adresse(&self) -> String {
         format!(
@@ -249,6 +827,11 @@ impl ServerConfig {
         )
     }
 
+    /// Gibt das Zeitlimit fuer das Herunterfahren des Commanders zurueck
+    pub fn commander_shutdown_zeitlimit(&self) -> Duration {
+        Duration::from_secs(self.commander.shutdown_zeitlimit_sek)
+    }
+
     /// Gibt die Bind-Adresse fuer den Commander TCP/TLS-Server zurueck
     pub fn commander_tcp_bind_adresse(&self) -> String {
         format!("{}:{}", self.netzwerk.bind_adresse, self.commander.tcp_port)
@@ -259,6 +842,58 @@ impl ServerConfig {
         format!("{}:{}", self.netzwerk.bind_adresse, self.netzwerk.grpc_port)
     }
 
+    /// Gibt die Bind-Adresse fuer den Datei-Server zurueck
+    pub fn datei_http_bind_adresse(&self) -> String {
+        format!("{}:{}", self.netzwerk.bind_adresse, self.dateien.http_port)
+    }
+
+    /// Von aussen erreichbare Basis-URL des Datei-Servers (ohne `/` am Ende)
+    fn datei_basis_url(&self) -> String {
+        match &self.dateien.oeffentliche_url {
+            Some(url) => url.trim_end_matches('/').to_string(),
+            None => format!("http://{}", self.datei_http_bind_adresse()),
+        }
+    }
+
+    /// Gibt die Konfiguration des Kontodienstes zurueck
+    pub fn konto_konfig(&self) -> KontoKonfig {
+        KontoKonfig {
+            download_basis_url: format!("{}/files/export", self.datei_basis_url()),
+            nachrichten_richtlinie: self.konten.nachrichten_bei_loeschung,
+            export_gueltigkeit: chrono::Duration::hours(self.konten.export_gueltigkeit_std as i64),
+            export_sperrfrist: chrono::Duration::hours(self.konten.export_sperrfrist_std as i64),
+        }
+    }
+
+    /// Gibt die Konfiguration fuer Download-Links von Dateianhaengen zurueck
+    pub fn download_konfig(&self) -> DownloadKonfig {
+        DownloadKonfig {
+            basis_url: format!("{}/files/download", self.datei_basis_url()),
+            gueltigkeit: chrono::Duration::minutes(self.dateien.download_gueltigkeit_min as i64),
+        }
+    }
+
+    /// Gibt die Konfiguration fuer Upload-Links von Dateianhaengen zurueck
+    pub fn upload_konfig(&self) -> UploadKonfig {
+        UploadKonfig {
+            basis_url: format!("{}/files/upload", self.datei_basis_url()),
+            gueltigkeit: chrono::Duration::minutes(self.dateien.upload_gueltigkeit_min as i64),
+        }
+    }
+
+    /// Gibt das Auswertungsintervall der Alarmierung zurueck
+    pub fn alarm_intervall(&self) -> Duration {
+        Duration::from_secs(self.alarm.intervall_sek.max(1))
+    }
+
+    /// Gibt die Grenzen der Flap-Erkennung zurueck
+    pub fn flap_grenzen(&self) -> FlapGrenzen {
+        FlapGrenzen {
+            fenster: Duration::from_secs(self.alarm.flap_fenster_min * 60),
+            max_wechsel: self.alarm.flap_max_wechsel,
+        }
+    }
+
     /// Gibt die Bind-Adresse fuer den Observability-Server zurueck
     pub fn observability_bind_adresse(&self) -> String {
         format!("{}:{}", self.netzwerk.bind_adresse, self.observability.port)
@@ -278,6 +913,34 @@ mod tests {
         assert_eq!(cfg.logging.level, "info");
     }
 
+    #[test]
+    fn datenbank_backend_aus_typ() {
+        let mut cfg = ServerConfig::default();
+        let db = cfg.datenbank_config().unwrap();
+        assert_eq!(db.backend, DatabaseBackend::Sqlite);
+        assert!(db.sqlite_wal);
+
+        cfg.datenbank.typ = "PostgreSQL".into();
+        cfg.datenbank.url = "postgres://speakeasy@localhost/speakeasy".into();
+        let db = cfg.datenbank_config().unwrap();
+        assert_eq!(db.backend, DatabaseBackend::Postgres);
+        assert_eq!(db.url, "postgres://speakeasy@localhost/speakeasy");
+
+        cfg.datenbank.typ = "mysql".into();
+        assert!(cfg.datenbank_config().is_err());
+    }
+
+    #[test]
+    fn pruefsumme_folgt_der_konfiguration() {
+        let mut cfg = ServerConfig::default();
+        let standard = cfg.pruefsumme();
+        assert_eq!(standard.len(), 16);
+        assert_eq!(standard, ServerConfig::default().pruefsumme());
+
+        cfg.server.max_clients = 64;
+        assert_ne!(cfg.pruefsumme(), standard);
+    }
+
     #[test]
     fn bind_adressen() {
         let cfg = ServerConfig::default();
@@ -285,6 +948,209 @@ mod tests {
         assert_eq!(cfg.udp_bind_adresse(), "0.0.0.0:9987");
     }
 
+    #[test]
+    fn effektive_konfiguration_als_toml() {
+        // Sicherungen legen die effektive Konfiguration als TOML ab
+        let mut cfg = ServerConfig::default();
+        cfg.dateien.speicher_verzeichnis = "/srv/speakeasy/dateien".into();
+        let text = toml::to_string(&cfg).unwrap();
+        let zurueck: ServerConfig = toml::from_str(&text).unwrap();
+        assert_eq!(
+            zurueck.dateien.speicher_verzeichnis,
+            "/srv/speakeasy/dateien"
+        );
+        assert_eq!(zurueck.netzwerk.tcp_port, cfg.netzwerk.tcp_port);
+    }
+
+    #[test]
+    fn registrierung_aus_toml() {
+        assert_eq!(
+            ServerConfig::default().server.registrierung,
+            RegistrierungsModus::NurEinladung
+        );
+
+        let cfg: ServerConfig = toml::from_str("[server]\nregistrierung = \"closed\"\n").unwrap();
+        assert_eq!(cfg.server.registrierung, RegistrierungsModus::Geschlossen);
+
+        assert!(toml::from_str::<ServerConfig>("[server]\nregistrierung = \"jeder\"\n").is_err());
+    }
+
+    #[test]
+    fn erlaubte_presets_aus_toml() {
+        assert!(ServerConfig::default().audio.erlaubte_presets.is_empty());
+
+        let cfg: ServerConfig =
+            toml::from_str("[audio]\nerlaubte_presets = [\"speech\", \"low_bandwidth\"]\n")
+                .unwrap();
+        assert_eq!(
+            cfg.audio.erlaubte_presets,
+            vec![AudioPreset::Speech, AudioPreset::LowBandwidth]
+        );
+    }
+
+    #[test]
+    fn voice_dscp_aus_toml() {
+        assert_eq!(ServerConfig::default().voice_dscp(), Some(46));
+
+        let cfg: ServerConfig = toml::from_str("[netzwerk]\nvoice_dscp = 0\n").unwrap();
+        assert_eq!(cfg.voice_dscp(), None);
+
+        let cfg: ServerConfig = toml::from_str("[netzwerk]\nvoice_dscp = 34\n").unwrap();
+        assert_eq!(cfg.voice_dscp(), Some(34));
+    }
+
+    #[test]
+    fn voice_socket_puffer_aus_toml() {
+        let cfg = ServerConfig::default();
+        assert_eq!(cfg.netzwerk.voice_empfangspuffer_bytes, None);
+
+        let cfg: ServerConfig =
+            toml::from_str("[netzwerk]\nvoice_empfangspuffer_bytes = 4194304\n").unwrap();
+        assert_eq!(
+            cfg.netzwerk.voice_empfangspuffer_bytes,
+            Some(4 * 1024 * 1024)
+        );
+        assert_eq!(cfg.netzwerk.voice_sendepuffer_bytes, None);
+    }
+
+    #[test]
+    fn voice_ping_aus_toml() {
+        let limits = ServerConfig::default().voice_ping().unwrap();
+        assert_eq!(limits.burst, 10);
+
+        let cfg: ServerConfig =
+            toml::from_str("[netzwerk]\nvoice_ping_pro_sek = 2.5\nvoice_ping_burst = 4\n").unwrap();
+        let limits = cfg.voice_ping().unwrap();
+        assert_eq!(limits.pro_sekunde, 2.5);
+        assert_eq!(limits.burst, 4);
+
+        let cfg: ServerConfig = toml::from_str("[netzwerk]\nvoice_ping_pro_sek = 0\n").unwrap();
+        assert_eq!(cfg.voice_ping(), None);
+    }
+
+    #[test]
+    fn paket_ttl_aus_toml() {
+        assert_eq!(ServerConfig::default().audio.paket_ttl_ms, None);
+
+        let cfg: ServerConfig = toml::from_str("[audio]\npaket_ttl_ms = 500\n").unwrap();
+        assert_eq!(cfg.audio.paket_ttl_ms, Some(500));
+    }
+
+    #[test]
+    fn zeitstempel_glaettung_aus_toml() {
+        assert_eq!(
+            ServerConfig::default().audio.zeitstempel_glaetten_ab_ms,
+            None
+        );
+
+        let cfg: ServerConfig =
+            toml::from_str("[audio]\nzeitstempel_glaetten_ab_ms = 1000\n").unwrap();
+        assert_eq!(cfg.audio.zeitstempel_glaetten_ab_ms, Some(1000));
+    }
+
+    #[test]
+    fn zeitlimits_aus_toml() {
+        assert_eq!(ServerConfig::default().zeitlimits(), Zeitlimits::default());
+
+        let cfg: ServerConfig =
+            toml::from_str("[datenbank]\ntimeout_schreiben_ms = 10000\n").unwrap();
+        assert_eq!(cfg.zeitlimits().schreiben, Duration::from_secs(10));
+        assert_eq!(cfg.zeitlimits().lesen, Duration::from_secs(2));
+    }
+
+    #[test]
+    fn anfrage_limits_aus_toml() {
+        assert_eq!(
+            ServerConfig::default().anfrage_limits(),
+            AnfrageLimits::default()
+        );
+
+        let cfg: ServerConfig =
+            toml::from_str("[server]\nmax_anfragen_pro_verbindung = 4\nmax_db_anfragen = 0\n")
+                .unwrap();
+        let limits = cfg.anfrage_limits();
+        assert_eq!(limits.pro_verbindung, 4);
+        assert_eq!(limits.pro_benutzer, 16);
+        assert_eq!(limits.db_gleichzeitig, 0);
+    }
+
+    #[test]
+    fn drossel_limits_aus_toml() {
+        assert_eq!(
+            ServerConfig::default().drossel_limits(),
+            DrosselLimits::default()
+        );
+
+        let cfg: ServerConfig = toml::from_str(
+            "[drosselung]\nchat_pro_minute = 30\nchat_stoss = 3\nip_sperre_sek = 600\n",
+        )
+        .unwrap();
+        let limits = cfg.drossel_limits();
+        assert_eq!(limits.chat, EimerLimit::neu(30, 3));
+        assert_eq!(limits.kanal, DrosselLimits::default().kanal);
+        assert_eq!(limits.ip_sperre, Some(Duration::from_secs(600)));
+    }
+
+    #[test]
+    fn replay_konfig_aus_toml() {
+        assert_eq!(
+            ServerConfig::default().replay_konfig(),
+            ReplayKonfig::default()
+        );
+
+        let cfg: ServerConfig = toml::from_str("[server]\nreplay_ring_groesse = 0\n").unwrap();
+        let replay = cfg.replay_konfig();
+        assert_eq!(replay.groesse, 0);
+        assert_eq!(replay.aufbewahrung, Duration::from_secs(300));
+    }
+
+    #[test]
+    fn einladungs_limits_aus_toml() {
+        assert_eq!(
+            ServerConfig::default().einladungs_limits(),
+            EinladungsLimits::default()
+        );
+
+        let cfg: ServerConfig = toml::from_str(
+            "[server]\neinladung_gueltigkeit_sek = 30\nmax_offene_einladungen = 0\n",
+        )
+        .unwrap();
+        let limits = cfg.einladungs_limits();
+        assert_eq!(limits.gueltigkeit, Duration::from_secs(30));
+        assert_eq!(limits.offen_pro_ziel, 0);
+        assert_eq!(limits.pro_benutzer, 10);
+    }
+
+    #[test]
+    fn audit_puffer_aus_toml() {
+        let standard = ServerConfig::default().audit_puffer();
+        assert_eq!(standard.batch_groesse, 50);
+        assert_eq!(standard.intervall, Duration::from_millis(250));
+
+        let cfg: ServerConfig =
+            toml::from_str("[datenbank]\naudit_batch_groesse = 200\naudit_intervall_ms = 1000\n")
+                .unwrap();
+        assert_eq!(cfg.audit_puffer().batch_groesse, 200);
+        assert_eq!(cfg.audit_puffer().intervall, Duration::from_secs(1));
+        assert_eq!(cfg.audit_puffer().queue_groesse, 4096);
+    }
+
+    #[test]
+    fn afk_richtlinie_aus_toml() {
+        assert!(ServerConfig::default()
+            .afk_richtlinie()
+            .aktiver_kanal()
+            .is_none());
+
+        let cfg: ServerConfig = toml::from_str(
+            "[server]\nafk_timeout_sek = 900\nafk_kanal = \"6f1c2d3e-4a5b-4c6d-8e7f-901234567890\"\n",
+        )
+        .unwrap();
+        let richtlinie = cfg.afk_richtlinie();
+        assert_eq!(richtlinie.schwelle, Duration::from_secs(900));
+        assert!(richtlinie.aktiver_kanal().is_some());
+    }
+
     #[test]
     fn config_aus_toml_string() {
         let toml = r#"
@@ -302,4 +1168,216 @@ mod tests {
         // Nicht angegebene Felder behalten Standardwerte
         assert_eq!(cfg.netzwerk.udp_port, 9987);
     }
+
+    #[test]
+    fn zugriffs_log_modus_aus_toml() {
+        let toml = r#"
+            [dateien]
+            zugriffs_log = "aggregiert"
+            zugriffs_log_aufbewahrung_tage = 30
+        "#;
+        let cfg: ServerConfig = toml::from_str(toml).unwrap();
+        assert_eq!(cfg.dateien.zugriffs_log, ZugriffsLogModus::Aggregiert);
+        assert_eq!(cfg.dateien.zugriffs_log_aufbewahrung_tage, 30);
+        assert_eq!(cfg.dateien.zugriffs_log_queue, 1024);
+    }
+
+    #[test]
+    fn commander_mtls_aus_toml() {
+        assert!(ServerConfig::default().commander.tls.is_none());
+
+        let toml = r#"
+            [commander.tls]
+            zertifikat = "certs/commander.pem"
+            schluessel = "certs/commander.key"
+            client_ca = "certs/clients-ca.pem"
+            client_zertifikate = "optional"
+            zertifikats_auth = "ersetzend"
+
+            [[commander.tls.zuordnungen]]
+            name = "backup.example.org"
+            benutzer = "backup"
+            scopes = ["cmd:serverinfo", "cmd:channellist"]
+        "#;
+        let cfg: ServerConfig = toml::from_str(toml).unwrap();
+        let tls = cfg.commander.tls.unwrap();
+        let konfig = tls.tls_konfig();
+        assert_eq!(konfig.client_modus, ClientZertModus::Optional);
+        assert_eq!(konfig.nachlade_intervall, STANDARD_NACHLADE_INTERVALL);
+        let zuordnungen = tls.ersetzende_zuordnungen().unwrap();
+        assert_eq!(zuordnungen[0].benutzer, "backup");
+        assert_eq!(zuordnungen[0].scopes.len(), 2);
+    }
+
+    #[test]
+    fn commander_shutdown_zeitlimit_aus_toml() {
+        assert_eq!(
+            ServerConfig::default().commander_shutdown_zeitlimit(),
+            Duration::from_secs(10)
+        );
+        let toml = r#"
+            [commander]
+            shutdown_zeitlimit_sek = 3
+        "#;
+        let cfg: ServerConfig = toml::from_str(toml).unwrap();
+        assert_eq!(cfg.commander_shutdown_zeitlimit(), Duration::from_secs(3));
+    }
+
+    #[test]
+    fn serverquery_aus_toml() {
+        let standard = ServerConfig::default();
+        assert!(!standard.commander.tcp_aktiviert);
+        assert!(standard.commander.tcp_tls.is_none());
+
+        let toml = r#"
+            [commander]
+            tcp_aktiviert = true
+            tcp_port = 10022
+
+            [commander.tcp_tls]
+            zertifikat = "certs/query.pem"
+            schluessel = "certs/query.key"
+        "#;
+        let cfg: ServerConfig = toml::from_str(toml).unwrap();
+        assert!(cfg.commander.tcp_aktiviert);
+        assert!(cfg.commander_tcp_bind_adresse().ends_with(":10022"));
+        let konfig = cfg.commander.tcp_tls.unwrap().tls_konfig();
+        assert_eq!(konfig.zertifikat.to_str(), Some("certs/query.pem"));
+        assert!(konfig.client_ca.is_none());
+    }
+
+    #[test]
+    fn zertifikate_ergaenzen_standardmaessig_nur() {
+        let toml = r#"
+            [commander.tls]
+            zertifikat = "certs/commander.pem"
+            schluessel = "certs/commander.key"
+            client_ca = "certs/clients-ca.pem"
+        "#;
+        let cfg: ServerConfig = toml::from_str(toml).unwrap();
+        let tls = cfg.commander.tls.unwrap();
+        assert_eq!(tls.tls_konfig().client_modus, ClientZertModus::Pflicht);
+        assert!(tls.ersetzende_zuordnungen().is_none());
+    }
+
+    #[test]
+    fn konten_aus_toml() {
+        let toml = r#"
+            [dateien]
+            oeffentliche_url = "https://voice.example.org/"
+
+            [konten]
+            nachrichten_bei_loeschung = "leeren"
+            export_gueltigkeit_std = 48
+        "#;
+        let cfg: ServerConfig = toml::from_str(toml).unwrap();
+        let konfig = cfg.konto_konfig();
+        assert_eq!(
+            konfig.download_basis_url,
+            "https://voice.example.org/files/export"
+        );
+        assert_eq!(konfig.nachrichten_richtlinie, NachrichtenRichtlinie::Leeren);
+        assert_eq!(konfig.export_gueltigkeit, chrono::Duration::hours(48));
+        assert_eq!(konfig.export_sperrfrist, chrono::Duration::hours(24));
+
+        let standard = ServerConfig::default().konto_konfig();
+        assert_eq!(
+            standard.download_basis_url,
+            "http://0.0.0.0:9301/files/export"
+        );
+    }
+
+    #[test]
+    fn download_links_aus_toml() {
+        let toml = r#"
+            [dateien]
+            oeffentliche_url = "https://voice.example.org/"
+            download_gueltigkeit_min = 3
+        "#;
+        let cfg: ServerConfig = toml::from_str(toml).unwrap();
+        let konfig = cfg.download_konfig();
+        assert_eq!(konfig.basis_url, "https://voice.example.org/files/download");
+        assert_eq!(konfig.gueltigkeit, chrono::Duration::minutes(3));
+
+        let standard = ServerConfig::default().download_konfig();
+        assert_eq!(standard.basis_url, "http://0.0.0.0:9301/files/download");
+        assert_eq!(standard.gueltigkeit, chrono::Duration::minutes(10));
+    }
+
+    #[test]
+    fn upload_links_aus_toml() {
+        let toml = r#"
+            [dateien]
+            oeffentliche_url = "https://voice.example.org"
+            upload_gueltigkeit_min = 30
+        "#;
+        let cfg: ServerConfig = toml::from_str(toml).unwrap();
+        let konfig = cfg.upload_konfig();
+        assert_eq!(konfig.basis_url, "https://voice.example.org/files/upload");
+        assert_eq!(konfig.gueltigkeit, chrono::Duration::minutes(30));
+
+        let standard = ServerConfig::default().upload_konfig();
+        assert_eq!(standard.basis_url, "http://0.0.0.0:9301/files/upload");
+        assert_eq!(standard.gueltigkeit, chrono::Duration::minutes(10));
+    }
+
+    #[test]
+    fn feature_warnungen_fuer_fehlende_features() {
+        let alle = ["plugins", "grpc", "observability", "tcp-commander"];
+        let toml = r#"
+            [netzwerk]
+            grpc_port = 10500
+            [commander]
+            tcp_aktiviert = true
+            [plugins]
+            aktiviert = true
+        "#;
+        let cfg: ServerConfig = toml::from_str(toml).unwrap();
+        assert!(cfg.feature_warnungen(&alle).is_empty());
+
+        let warnungen = cfg.feature_warnungen(&[]);
+        assert_eq!(warnungen.len(), 5);
+        assert!(warnungen[0].contains("[plugins]"));
+        assert!(warnungen[1].contains("`grpc`"));
+        assert!(warnungen[4].contains("`tcp-commander`"));
+
+        // Standardwerte verweisen nur auf Observability und Alarmierung
+        let standard = ServerConfig::default().feature_warnungen(&["plugins", "grpc"]);
+        assert_eq!(standard.len(), 2);
+        assert!(standard.iter().all(|w| w.contains("`observability`")));
+    }
+
+    #[test]
+    fn alarm_aus_toml() {
+        let standard = ServerConfig::default();
+        assert_eq!(standard.alarm.regeln.len(), 4);
+        assert_eq!(standard.flap_grenzen(), FlapGrenzen::default());
+
+        let toml = r#"
+            [alarm]
+            intervall_sek = 10
+            webhook_url = "https://hooks.example.org/speakeasy"
+            flap_max_wechsel = 0
+
+            [[alarm.regeln]]
+            name = "voice_verlust_hoch"
+            metrik = "voice_paketverlust"
+            vergleich = "ueber"
+            schwelle = 20.0
+            dauer_sek = 60
+            schwere = "kritisch"
+            ziele = ["webhook"]
+        "#;
+        let cfg: ServerConfig = toml::from_str(toml).unwrap();
+        assert_eq!(cfg.alarm_intervall(), Duration::from_secs(10));
+        assert_eq!(cfg.flap_grenzen().max_wechsel, 0);
+        assert_eq!(cfg.alarm.regeln.len(), 1);
+        assert_eq!(cfg.alarm.regeln[0].name, "voice_verlust_hoch");
+        assert_eq!(cfg.alarm.regeln[0].dauer_sek, 60);
+
+        // Regeln muessen sich als effektive Konfiguration ablegen lassen
+        let text = toml::to_string(&standard).unwrap();
+        let zurueck: ServerConfig = toml::from_str(&text).unwrap();
+        assert_eq!(zurueck.alarm.regeln, standard.alarm.regeln);
+    }
 }
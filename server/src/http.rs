@@ -0,0 +1,315 @@
+//! Datei-Server fuer Downloads per Einmal-Link
+//!
+//! Endpunkte:
+//! - `GET /files/export/:token` – Konto-Export abholen (genau einmal)
+//! - `GET /files/download/:token` – Dateianhang ueber signierten Link
+//! - `PUT /files/upload/:token` – Dateianhang ueber signierten Link hochladen
+//!
+//! Der Token ist die einzige Berechtigung: wer ihn kennt, erhaelt das
+//! Archiv. Unbekannte, abgelaufene und bereits eingeloeste Tokens werden
+//! gleich beantwortet, damit sich gueltige Tokens nicht erraten lassen.
+//!
+//! Download-Links sind signiert statt gespeichert und bis zum Ablauf
+//! mehrfach einloesbar. Weil die Signatur vor allem anderen geprueft wird,
+//! darf die Antwort hier unterscheiden: `403` fuer ungueltige, `410` fuer
+//! abgelaufene Links und `404` fuer inzwischen geloeschte Dateien.
+//!
+//! Upload-Links tragen die angekuendigte Groesse in der Signatur. Der Body
+//! wird beim Empfang in eine Zwischendatei geschrieben, dabei gehasht und
+//! gezaehlt, und hoechstens bis zu dieser Groesse gelesen; erst danach
+//! uebernimmt der Storage die Datei. Abweichungen (`400`),
+//! volle Kontingente (`507`) und falsche Pruefsummen (`422`) erhalten je
+//! eine eigene Antwort, damit der Client den Grund anzeigen kann.
+
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::sync::{Arc, OnceLock};
+
+use axum::{
+    body::Body,
+    extract::{Path, State},
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+    routing::{get, put},
+    Json, Router,
+};
+use http_body_util::BodyExt;
+use sha2::{Digest, Sha256};
+use speakeasy_chat::{
+    ChatError, ChatNachricht, DateiDienst, KontoDienst, UploadDienst, Zwischendatei,
+};
+use tokio::io::AsyncWriteExt;
+use tokio::sync::watch;
+use uuid::Uuid;
+
+/// Meldet die Chat-Nachricht eines abgeschlossenen Uploads an den Kanal
+pub type UploadMelder = Arc<dyn Fn(ChatNachricht) + Send + Sync>;
+
+/// Dienste hinter dem Datei-Server
+#[derive(Clone)]
+pub struct DateiServerState {
+    pub konto: Arc<dyn KontoDienst>,
+    pub dateien: Arc<dyn DateiDienst>,
+    pub uploads: Arc<dyn UploadDienst>,
+    /// Wird gesetzt, sobald der Signaling-Server steht
+    pub upload_melder: Arc<OnceLock<UploadMelder>>,
+}
+
+/// Erstellt den Router des Datei-Servers
+pub fn datei_router(state: DateiServerState) -> Router {
+    Router::new()
+        .route("/files/export/:token", get(export_herunterladen))
+        .route("/files/download/:token", get(datei_herunterladen))
+        .route("/files/upload/:token", put(datei_hochladen))
+        .with_state(state)
+}
+
+/// Startet den Datei-Server und haelt ihn bis zum Shutdown am Laufen
+pub async fn datei_server_starten(
+    bind_addr: SocketAddr,
+    state: DateiServerState,
+    mut shutdown_rx: watch::Receiver<bool>,
+) -> anyhow::Result<()> {
+    let listener = tokio::net::TcpListener::bind(bind_addr)
+        .await
+        .map_err(|e| anyhow::anyhow!("Datei-Server konnte {bind_addr} nicht binden: {e}"))?;
+    tracing::info!(addr = %bind_addr, "Datei-Server gestartet");
+
+    axum::serve(listener, datei_router(state))
+        .with_graceful_shutdown(async move {
+            let _ = shutdown_rx.wait_for(|beenden| *beenden).await;
+        })
+        .await?;
+    Ok(())
+}
+
+/// `GET /files/export/:token`
+async fn export_herunterladen(
+    State(state): State<DateiServerState>,
+    Path(token): Path<String>,
+) -> Response {
+    match state.konto.export_abholen(&token).await {
+        Ok(archiv) => (
+            [
+                (header::CONTENT_TYPE, "application/json"),
+                (
+                    header::CONTENT_DISPOSITION,
+                    "attachment; filename=\"speakeasy-export.json\"",
+                ),
+                (header::CACHE_CONTROL, "no-store"),
+            ],
+            archiv,
+        )
+            .into_response(),
+        Err(ChatError::TokenUngueltig(_)) => {
+            (StatusCode::NOT_FOUND, "Export nicht gefunden").into_response()
+        }
+        Err(e) => {
+            tracing::error!(fehler = %e, "Konto-Export konnte nicht ausgeliefert werden");
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
+
+/// `GET /files/download/:token`
+async fn datei_herunterladen(
+    State(state): State<DateiServerState>,
+    Path(token): Path<String>,
+) -> Response {
+    match state.dateien.ausliefern(&token).await {
+        Ok((info, daten)) => {
+            let mime = HeaderValue::from_str(&info.mime_type)
+                .unwrap_or(HeaderValue::from_static("application/octet-stream"));
+            let disposition = format!(
+                "attachment; filename=\"{}\"",
+                dateiname_fuer_header(&info.filename)
+            );
+            (
+                [
+                    (header::CONTENT_TYPE, mime),
+                    (
+                        header::CONTENT_DISPOSITION,
+                        HeaderValue::from_str(&disposition)
+                            .unwrap_or(HeaderValue::from_static("attachment")),
+                    ),
+                    (header::CACHE_CONTROL, HeaderValue::from_static("no-store")),
+                ],
+                daten,
+            )
+                .into_response()
+        }
+        Err(ChatError::TokenUngueltig(_)) => {
+            (StatusCode::FORBIDDEN, "Download-Link ungueltig").into_response()
+        }
+        Err(ChatError::TokenAbgelaufen(_)) => {
+            (StatusCode::GONE, "Download-Link abgelaufen").into_response()
+        }
+        Err(ChatError::DateiNichtGefunden(_)) => {
+            (StatusCode::NOT_FOUND, "Datei nicht gefunden").into_response()
+        }
+        Err(e) => {
+            tracing::error!(fehler = %e, "Datei konnte nicht ausgeliefert werden");
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
+
+/// `PUT /files/upload/:token`
+async fn datei_hochladen(
+    State(state): State<DateiServerState>,
+    Path(token): Path<String>,
+    headers: HeaderMap,
+    body: Body,
+) -> Response {
+    let freigabe = match state.uploads.freigabe_pruefen(&token) {
+        Ok(freigabe) => freigabe,
+        Err(e) => return upload_fehler(e),
+    };
+
+    // Offensichtliche Abweichungen ablehnen, bevor der Body gelesen wird
+    let angekuendigt = headers
+        .get(header::CONTENT_LENGTH)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.parse::<u64>().ok());
+    if let Some(laenge) = angekuendigt.filter(|l| *l != freigabe.size_bytes) {
+        return upload_fehler(ChatError::GroesseAbweichend {
+            erwartet: freigabe.size_bytes,
+            erhalten: laenge,
+        });
+    }
+
+    // Entfernt die Zwischendatei auf jedem Fehlerweg; nach Erfolg hat der
+    // Storage sie bereits verschoben
+    let aufraeumen = ZwischendateiEntfernen(std::env::temp_dir().join(format!(
+        "speakeasy-upload-{}-{}.part",
+        freigabe.file_id.simple(),
+        Uuid::new_v4().simple()
+    )));
+    let datei = match body_zwischenspeichern(body, &aufraeumen.0, freigabe.size_bytes).await {
+        Ok(datei) => datei,
+        Err(antwort) => return antwort,
+    };
+
+    match state.uploads.hochladen_aus_datei(&token, &datei).await {
+        Ok((info, nachricht)) => {
+            let antwort = serde_json::json!({
+                "file_id": info.id,
+                "message_id": nachricht.id,
+                "size_bytes": info.size_bytes,
+                "created_at": nachricht.created_at,
+            });
+            if let Some(melden) = state.upload_melder.get() {
+                melden(nachricht);
+            }
+            tracing::info!(
+                file_id = %info.id,
+                user_id = %freigabe.user_id,
+                groesse = info.size_bytes,
+                "Datei hochgeladen"
+            );
+            (StatusCode::OK, Json(antwort)).into_response()
+        }
+        Err(e) => upload_fehler(e),
+    }
+}
+
+/// Schreibt den Body nach `pfad` und bricht nach `max_bytes` Bytes ab
+async fn body_zwischenspeichern(
+    mut body: Body,
+    pfad: &std::path::Path,
+    max_bytes: u64,
+) -> Result<Zwischendatei, Response> {
+    let intern = |e: std::io::Error| {
+        tracing::error!(%e, path = %pfad.display(), "Zwischendatei fuer Upload nicht schreibbar");
+        (StatusCode::INTERNAL_SERVER_ERROR, "Interner Fehler").into_response()
+    };
+    let mut datei = tokio::fs::File::create(pfad).await.map_err(intern)?;
+    let mut hasher = Sha256::new();
+    let mut groesse: u64 = 0;
+
+    while let Some(frame) = body.frame().await {
+        let frame =
+            frame.map_err(|_| (StatusCode::BAD_REQUEST, "Upload abgebrochen").into_response())?;
+        let Ok(daten) = frame.into_data() else {
+            continue;
+        };
+        groesse += daten.len() as u64;
+        if groesse > max_bytes {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                format!("Dateigroesse weicht ab: mehr als angekuendigte {max_bytes} Bytes"),
+            )
+                .into_response());
+        }
+        hasher.update(&daten);
+        datei.write_all(&daten).await.map_err(intern)?;
+    }
+    datei.flush().await.map_err(intern)?;
+
+    Ok(Zwischendatei {
+        pfad: pfad.to_path_buf(),
+        size_bytes: groesse,
+        sha256: format!("{:x}", hasher.finalize()),
+    })
+}
+
+/// Loescht die Zwischendatei eines Uploads beim Verlassen des Handlers
+struct ZwischendateiEntfernen(PathBuf);
+
+impl Drop for ZwischendateiEntfernen {
+    fn drop(&mut self) {
+        // Fehlt nach erfolgreicher Uebernahme durch den Storage
+        let _ = std::fs::remove_file(&self.0);
+    }
+}
+
+/// Bildet Fehler eines Uploads auf Statuscodes mit lesbarer Begruendung ab
+fn upload_fehler(fehler: ChatError) -> Response {
+    match fehler {
+        ChatError::TokenUngueltig(_) => {
+            (StatusCode::FORBIDDEN, "Upload-Link ungueltig").into_response()
+        }
+        ChatError::TokenAbgelaufen(_) => {
+            (StatusCode::GONE, "Upload-Link abgelaufen").into_response()
+        }
+        ChatError::KeineBerechtigung(_) => (
+            StatusCode::FORBIDDEN,
+            "Upload gehoert einem anderen Benutzer",
+        )
+            .into_response(),
+        ChatError::DateiNichtGefunden(_) => (
+            StatusCode::NOT_FOUND,
+            "Upload nicht gefunden oder bereits abgeschlossen",
+        )
+            .into_response(),
+        e @ ChatError::GroesseAbweichend { .. } => {
+            (StatusCode::BAD_REQUEST, e.to_string()).into_response()
+        }
+        e @ ChatError::DateiZuGross { .. } => {
+            (StatusCode::PAYLOAD_TOO_LARGE, e.to_string()).into_response()
+        }
+        e @ ChatError::KontingentErschoepft { .. } => {
+            (StatusCode::INSUFFICIENT_STORAGE, e.to_string()).into_response()
+        }
+        e @ ChatError::PruefsummeAbweichend => {
+            (StatusCode::UNPROCESSABLE_ENTITY, e.to_string()).into_response()
+        }
+        e => {
+            tracing::error!(fehler = %e, "Upload konnte nicht abgeschlossen werden");
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
+
+/// Ersetzt Zeichen, die im `Content-Disposition`-Header stoeren
+fn dateiname_fuer_header(name: &str) -> String {
+    name.chars()
+        .map(|c| {
+            if c == ' ' || (c.is_ascii_graphic() && c != '"' && c != '\\') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
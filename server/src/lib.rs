@@ -3,38 +3,118 @@
 //! Deklariert alle Server-Module und stellt den oeffentlichen Einstiegspunkt
 //! fuer Integrationstests bereit.
 
+#[cfg(feature = "observability")]
+pub mod alarmierung;
 pub mod config;
+pub mod http;
+#[cfg(feature = "observability")]
+mod telemetrie;
 
 use std::net::SocketAddr;
-use std::sync::Arc;
+use std::path::PathBuf;
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
 
+#[cfg(feature = "observability")]
+use alarmierung::{AlarmQuellen, Alarmierung};
 use anyhow::Result;
 use config::ServerConfig;
 
+use speakeasy_audio::AudioError;
 use speakeasy_auth::{ApiTokenStore, AuthService, BanService, PermissionService, SessionStore};
-use speakeasy_commander::rest::{CommanderState, ExecutorFn, TokenValidatorFn};
-use speakeasy_commander::{CommandExecutor, RateLimitKonfig, RateLimiter};
+use speakeasy_chat::{ChatError, DateiDienst, KontoDienst, StorageBackend, UploadDienst};
+use speakeasy_commander::commands::types::{
+    BereinigungsAuftrag, BereinigungsErgebnis, CommanderEreignis, KanalBereinigungInfo,
+    KodierterSound, KontoAuftrag, KontoExportErgebnis, KontoLoeschErgebnis, NotfallStummAuftrag,
+    NotfallStummErgebnis, SammelVerschiebung, SammelVerschiebungErgebnis, UebersprungenerClient,
+    VoiceDiagnoseInfo,
+};
+use speakeasy_commander::rest::{
+    BoxFuture, CommanderState, ExecutorFn, TokenValidatorFn, ZertifikatsValidatorFn,
+};
+use speakeasy_commander::sicherung::{self, SicherungsQuellen};
+use speakeasy_commander::tls::ClientIdentitaet;
+use speakeasy_commander::zeitplaner::{SystemUhr, Zeitplaner};
+use speakeasy_commander::{
+    CommandExecutor, CommanderError, CommanderResult, RateLimitKonfig, RateLimiter,
+    SignalingNotifier,
+};
+use speakeasy_core::types::{ChannelId, UserId};
 use speakeasy_db::{
-    models::{KanalTyp, NeuerKanal},
-    repository::{ChannelRepository, DatabaseBackend, DatabaseConfig, UserRepository},
-    SqliteDb,
+    einstellungen::ServerEinstellungen,
+    models::{KanalTyp, KanalbaumGrenzen, NeuerKanal},
+    motd::Motd,
+    repository::{ChannelRepository, UserRepository},
+    AuditPuffer, AuditSink, Datenbank,
 };
 // UserRepository explizit importiert fuer UFCS-Aufrufe
+use speakeasy_observability::{Befund, HealthState, StartPhase};
+#[cfg(feature = "plugins")]
 use speakeasy_plugin::{ManagerKonfiguration, PluginManager};
-use speakeasy_signaling::{server_state::SignalingConfig, SignalingServer};
+use speakeasy_protocol::control::{
+    ChannelTreeChanged, ClientsMoveAllRequest, ControlMessage, ControlPayload, ErrorCode,
+    MotdChangedEvent, MoveSkipReason,
+};
+use speakeasy_signaling::bereinigung::{self, Bereinigungsauftrag};
+use speakeasy_signaling::handlers::channel_handler::kanal_geaendert_melden;
+use speakeasy_signaling::handlers::client_handler::{client_trennen, clients_alle_verschieben};
+use speakeasy_signaling::moderation;
+use speakeasy_signaling::notfall::kanal_notfall_stumm;
+use speakeasy_signaling::server_state::{CodecRichtlinie, SignalingConfig, SignalingState};
+use speakeasy_signaling::soundboard::SoundQuelle;
+use speakeasy_signaling::{SignalingError, SignalingServer};
+#[cfg(feature = "observability")]
+use speakeasy_voice::telemetry::VoiceMetricsCollector;
 use speakeasy_voice::udp::{VoiceServer, VoiceServerConfig};
-use speakeasy_voice::{ChannelRouter, VoiceState};
+use speakeasy_voice::{
+    AktivitaetsTracker, ChannelRouter, NotfallStumm, SprecherTracker, VoiceState,
+};
+use uuid::Uuid;
 
 /// Standard-Passwort fuer den Admin-Benutzer beim ersten Start
 const ADMIN_STANDARD_PASSWORT: &str = "admin";
 /// Standard-Benutzername fuer den Admin
 const ADMIN_BENUTZERNAME: &str = "admin";
 
+/// Subsysteme, deren Bereitmeldung `/health/ready` abwartet
+const SUBSYSTEM_VOICE: &str = "voice";
+const SUBSYSTEM_SIGNALING: &str = "signaling";
+const SUBSYSTEM_COMMANDER: &str = "commander";
+
+/// Komponenten, deren Zustand `/health` zusaetzlich zu den Subsystemen meldet
+const KOMPONENTE_DATENBANK: &str = "database";
+#[cfg(feature = "observability")]
+const KOMPONENTE_SPEICHER: &str = "disk";
+
+/// Ab dieser Belegung des Dateispeichers gilt der Server als eingeschraenkt
+#[cfg(feature = "observability")]
+const SPEICHER_GRENZE_PROZENT: f64 = 95.0;
+
+/// Bleibt der Herzschlag von Voice- oder Signaling-Loop laenger aus, gilt
+/// das Subsystem als haengend
+const HERZSCHLAG_MAX_ALTER: Duration = Duration::from_secs(5);
+
+/// Optionale Subsysteme, mit denen dieses Binary gebaut wurde
+///
+/// Namen wie die Cargo-Features von `speakeasy-server`; der Commander meldet
+/// sie in der Server-Info.
+pub fn einkompilierte_features() -> Vec<&'static str> {
+    [
+        ("plugins", cfg!(feature = "plugins")),
+        ("grpc", cfg!(feature = "grpc")),
+        ("observability", cfg!(feature = "observability")),
+        ("tcp-commander", cfg!(feature = "tcp-commander")),
+    ]
+    .into_iter()
+    .filter_map(|(name, aktiv)| aktiv.then_some(name))
+    .collect()
+}
+
 /// Gemeinsamer Zustand des Servers (thread-safe, via Arc geteilt)
 pub struct ServerState {
-    pub auth_service: Arc<AuthService<SqliteDb>>,
-    pub permission_service: Arc<PermissionService<SqliteDb>>,
-    pub ban_service: Arc<BanService<SqliteDb>>,
+    pub auth_service: Arc<AuthService<Datenbank>>,
+    pub permission_service: Arc<PermissionService<Datenbank>>,
+    pub ban_service: Arc<BanService<Datenbank>>,
 }
 
 /// Haelt den laufenden Server-Zustand zusammen
@@ -51,16 +131,25 @@ impl Server {
     /// Startet alle Server-Subsysteme und laeuft bis zum Shutdown-Signal
     ///
     /// Reihenfolge:
-    /// 1. Datenbankverbindung herstellen und Migrationen ausfuehren
-    /// 2. Auth-, Permission- und Ban-Services initialisieren
-    /// 3. Erster Start: Admin-Benutzer anlegen wenn keine Benutzer vorhanden
-    /// 4. Chat-Service erstellen
-    /// 5. Voice-Server starten (UDP)
-    /// 6. Signaling-Server starten (TCP) – eigener Thread mit LocalSet
-    /// 7. Commander starten (REST + gRPC)
-    /// 8. Observability starten (Metriken + Health)
+    /// 1. Observability starten (Metriken + Health), `/health/ready` meldet
+    ///    ab hier die aktuelle Startphase
+    /// 2. Datenbankverbindung herstellen und Migrationen ausfuehren
+    /// 3. Auth-, Permission- und Ban-Services initialisieren
+    /// 4. Erster Start: Admin-Benutzer anlegen wenn keine Benutzer vorhanden
+    /// 5. Chat-Service erstellen
+    /// 6. Voice-Server starten (UDP)
+    /// 7. Signaling-Server starten (TCP) – eigener Thread mit LocalSet
+    /// 8. Commander starten (REST + gRPC, optional TCP-ServerQuery)
     /// 9. Plugin-Manager initialisieren
     /// 10. Auf Ctrl-C warten und Graceful Shutdown
+    ///
+    /// Schlaegt der Start fehl, bleibt der Observability-Server fuer
+    /// `observability.start_fehler_nachlauf_sek` mit dem Fehlergrund
+    /// erreichbar, bevor der Fehler zurueckgegeben wird.
+    ///
+    /// Schritte fuer Subsysteme ohne einkompiliertes Feature (`observability`,
+    /// `grpc`, `tcp-commander`, `plugins`) entfallen; Einstellungen dafuer
+    /// werden nur mit einer Warnung quittiert.
     pub async fn starten(self) -> Result<()> {
         tracing::info!(
             server_name = %self.config.server.name,
@@ -68,32 +157,181 @@ impl Server {
             udp = %self.config.udp_bind_adresse(),
             rest_port = self.config.commander.rest_port,
             grpc_port = self.config.netzwerk.grpc_port,
+            features = ?einkompilierte_features(),
             "Server startet"
         );
+        for warnung in self.config.feature_warnungen(&einkompilierte_features()) {
+            tracing::warn!("{warnung}");
+        }
+
+        // --- 1. Observability starten ---
+        let health = HealthState::neu();
+        let (obs_shutdown_tx, obs_shutdown_rx) = tokio::sync::watch::channel(false);
+        let obs_handle = self.observability_starten(&health, obs_shutdown_rx)?;
 
-        // --- 1. Datenbankverbindung ---
-        let db_config = DatabaseConfig {
-            backend: DatabaseBackend::Sqlite,
-            url: self.config.datenbank.url.clone(),
-            max_verbindungen: self.config.datenbank.max_verbindungen,
-            sqlite_wal: true,
+        let laufend = match self.hochfahren(&health).await {
+            Ok(laufend) => laufend,
+            Err(e) => {
+                tracing::error!(fehler = %e, "Serverstart fehlgeschlagen");
+                health.start_fehlgeschlagen(format!("{e:#}"));
+                if obs_handle.is_some() {
+                    let nachlauf =
+                        Duration::from_secs(self.config.observability.start_fehler_nachlauf_sek);
+                    tracing::info!(
+                        nachlauf_sek = nachlauf.as_secs(),
+                        "Observability bleibt fuer die Fehlerdiagnose erreichbar"
+                    );
+                    tokio::select! {
+                        _ = tokio::time::sleep(nachlauf) => {}
+                        _ = tokio::signal::ctrl_c() => {}
+                    }
+                }
+                let _ = obs_shutdown_tx.send(true);
+                if let Some(handle) = obs_handle {
+                    let _ = handle.await;
+                }
+                return Err(e);
+            }
         };
 
+        // --- 10. Warten auf Shutdown-Signal ---
+        tracing::info!(
+            "Server laeuft. Alle Subsysteme gestartet. Warte auf Shutdown-Signal (Ctrl-C)..."
+        );
+        tokio::signal::ctrl_c().await?;
+        tracing::info!("Shutdown-Signal empfangen, fahre Server herunter...");
+
+        laufend.herunterfahren().await;
+
+        // Observability zuletzt stoppen
+        let _ = obs_shutdown_tx.send(true);
+        if let Some(handle) = obs_handle {
+            let _ = handle.await;
+            tracing::debug!("Observability-Server gestoppt");
+        }
+
+        tracing::info!("Server erfolgreich heruntergefahren");
+
+        Ok(())
+    }
+
+    /// Startet den Observability-Server (Metriken + Health), sofern aktiviert
+    #[cfg(feature = "observability")]
+    fn observability_starten(
+        &self,
+        health: &HealthState,
+        shutdown_rx: tokio::sync::watch::Receiver<bool>,
+    ) -> Result<Option<tokio::task::JoinHandle<()>>> {
+        if !self.config.observability.aktiviert {
+            tracing::info!("Observability deaktiviert");
+            return Ok(None);
+        }
+        let obs_addr: SocketAddr = self.config.observability_bind_adresse().parse()?;
+        let obs_health = health.clone();
+        let handle = tokio::spawn(async move {
+            if let Err(e) = speakeasy_observability::observability_server_starten(
+                obs_addr,
+                obs_health,
+                shutdown_rx,
+            )
+            .await
+            {
+                tracing::error!(fehler = %e, "Observability-Server Fehler");
+            }
+        });
+        tracing::info!(
+            adresse = %self.config.observability_bind_adresse(),
+            "Observability-Server gestartet (Metriken + Health)"
+        );
+        Ok(Some(handle))
+    }
+
+    /// Ohne Feature `observability` gibt es weder `/metrics` noch `/health`
+    #[cfg(not(feature = "observability"))]
+    fn observability_starten(
+        &self,
+        _health: &HealthState,
+        _shutdown_rx: tokio::sync::watch::Receiver<bool>,
+    ) -> Result<Option<tokio::task::JoinHandle<()>>> {
+        tracing::info!("Observability nicht einkompiliert");
+        Ok(None)
+    }
+
+    /// Startet alle Subsysteme hinter der Observability und meldet den
+    /// Fortschritt an `health`
+    async fn hochfahren(&self, health: &HealthState) -> Result<Laufend> {
+        // Ohne diese Subsysteme gilt der Server nicht als bereit
+        for subsystem in [SUBSYSTEM_VOICE, SUBSYSTEM_SIGNALING, SUBSYSTEM_COMMANDER] {
+            health.subsystem_erwarten(subsystem);
+        }
+
+        // --- 2. Datenbankverbindung ---
+        let db_config = self.config.datenbank_config()?;
+
+        // Die URL wird nicht geloggt: bei PostgreSQL kann sie ein Passwort enthalten
         tracing::info!(
             backend = %db_config.backend,
-            url = %db_config.url,
             "Datenbankverbindung wird hergestellt"
         );
 
         let db = Arc::new(
-            SqliteDb::oeffnen(&db_config)
-                .await
-                .map_err(|e| anyhow::anyhow!("Datenbankverbindung fehlgeschlagen: {e}"))?,
+            Datenbank::oeffnen_mit_fortschritt(&db_config, |fortschritt| {
+                health.phase_setzen(StartPhase::SchemaMigration {
+                    aktuell: fortschritt.aktuell,
+                    gesamt: fortschritt.gesamt,
+                });
+            })
+            .await
+            .map_err(|e| anyhow::anyhow!("Datenbankverbindung fehlgeschlagen: {e}"))?,
         );
 
         tracing::info!("Datenbankverbindung hergestellt, Migrationen ausgefuehrt");
 
-        // --- 2. Auth-, Permission- und Ban-Services ---
+        // `/health` prueft die Datenbank bei jeder Anfrage mit `SELECT 1`
+        let db_pruefung = Arc::clone(&db);
+        health
+            .komponenten()
+            .pruefung_registrieren(KOMPONENTE_DATENBANK, move || {
+                let db = Arc::clone(&db_pruefung);
+                async move {
+                    if db.erreichbar().await {
+                        Befund::gesund()
+                    } else {
+                        Befund::ausgefallen("SELECT 1 fehlgeschlagen")
+                    }
+                }
+            });
+
+        // Startprotokoll: war der vorherige Lauf sauber beendet?
+        let shutdown_marker = PathBuf::from(&self.config.server.shutdown_marker);
+        let neustart = speakeasy_db::neustart::start_erfassen(
+            db.as_ref(),
+            &shutdown_marker,
+            env!("CARGO_PKG_VERSION"),
+            &self.config.pruefsumme(),
+        )
+        .await
+        .map_err(|e| anyhow::anyhow!("Serverstart konnte nicht protokolliert werden: {e}"))?;
+        if neustart.clean_shutdown {
+            tracing::info!(
+                config_hash = %neustart.config_hash,
+                vorheriger_lauf_sauber = true,
+                "Serverstart protokolliert"
+            );
+        } else {
+            tracing::warn!(
+                config_hash = %neustart.config_hash,
+                vorheriger_lauf_sauber = false,
+                "Vorheriger Lauf wurde nicht sauber beendet (Absturz oder Abbruch)"
+            );
+            #[cfg(feature = "observability")]
+            speakeasy_observability::metrics::globale_metriken()
+                .unclean_shutdowns_total
+                .inc();
+        }
+
+        // --- 3. Auth-, Permission- und Ban-Services ---
+        health.phase_setzen(StartPhase::DiensteErstellen);
         let session_store = SessionStore::neu();
         let session_store = SessionStore::neu_mit_cleanup(session_store);
 
@@ -107,33 +345,159 @@ impl Server {
 
         let permission_service = PermissionService::neu(Arc::clone(&db));
         let ban_service = BanService::neu(Arc::clone(&db));
+        // Abgelaufene Bans sperren ohnehin nicht mehr; der Task raeumt sie weg
+        BanService::cleanup_task_starten(Arc::clone(&ban_service));
 
         tracing::info!("Auth-, Permission- und Ban-Services initialisiert");
 
-        // --- 3. Erster Start: Admin-Benutzer und Default-Channel anlegen ---
+        // --- 4. Erster Start: Admin-Benutzer und Default-Channel anlegen ---
         ersten_start_initialisieren(&db, &auth_service).await?;
 
+        // Laufzeit-Einstellungen: fehlende Werte aus der Konfiguration vorbelegen,
+        // danach gilt die Datenbank
+        let standard_einstellungen = ServerEinstellungen {
+            name: self.config.server.name.clone(),
+            willkommensnachricht: self.config.server.willkommen.clone(),
+            max_clients: self.config.server.max_clients,
+            host_nachricht: None,
+            chat_bearbeitungsfrist_sek: self.config.server.chat_bearbeitungsfrist_sek,
+        };
+        let vorbelegt = standard_einstellungen
+            .vorbelegen(db.as_ref())
+            .await
+            .map_err(|e| anyhow::anyhow!("Server-Einstellungen nicht angelegt: {e}"))?;
+        let einstellungen = ServerEinstellungen::laden(db.as_ref(), &standard_einstellungen)
+            .await
+            .map_err(|e| anyhow::anyhow!("Server-Einstellungen nicht geladen: {e}"))?;
+        let motd = Motd::laden(db.as_ref())
+            .await
+            .map_err(|e| anyhow::anyhow!("MOTD nicht geladen: {e}"))?;
+        tracing::info!(
+            name = %einstellungen.name,
+            max_clients = einstellungen.max_clients,
+            motd_version = motd.version,
+            vorbelegt,
+            "Server-Einstellungen geladen"
+        );
+
         let _state = Arc::new(ServerState {
             auth_service: Arc::clone(&auth_service),
             permission_service: Arc::clone(&permission_service),
             ban_service: Arc::clone(&ban_service),
         });
 
-        // --- 4. Chat-Service ---
+        // --- 5. Chat-Service ---
         let chat_service = speakeasy_chat::ChatService::neu(Arc::clone(&db));
-        let _file_storage = Arc::new(speakeasy_chat::DiskStorage::new("data/files"));
-        tracing::info!("Chat-Service initialisiert");
+        let file_storage = Arc::new(speakeasy_chat::DiskStorage::new(
+            &self.config.dateien.speicher_verzeichnis,
+        ));
+        #[cfg(feature = "observability")]
+        speicher_pruefung_registrieren(health, &self.config.dateien.speicher_verzeichnis);
+        let zugriffs_log = speakeasy_chat::ZugriffsLogger::starten(
+            Arc::clone(&db),
+            speakeasy_chat::ZugriffsLogKonfig {
+                modus: self.config.dateien.zugriffs_log,
+                queue_groesse: self.config.dateien.zugriffs_log_queue,
+                aufbewahrung_tage: self.config.dateien.zugriffs_log_aufbewahrung_tage,
+            },
+        );
+        // Audit-Log gebuendelt schreiben (Commander und Signaling)
+        let audit_puffer = AuditPuffer::starten(Arc::clone(&db), self.config.audit_puffer())
+            .map_err(|e| anyhow::anyhow!("Audit-Writer konnte nicht gestartet werden: {e}"))?;
+        let file_service = speakeasy_chat::FileService::neu_mit_zugriffs_log(
+            Arc::clone(&db),
+            Arc::clone(&db),
+            Arc::clone(&file_storage),
+            Arc::clone(&zugriffs_log),
+        );
+        let konto_konfig = self.config.konto_konfig();
+        tracing::info!(
+            zugriffs_log = ?self.config.dateien.zugriffs_log,
+            nachrichten_bei_loeschung = konto_konfig.nachrichten_richtlinie.als_str(),
+            "Chat-, Datei- und Kontodienst initialisiert"
+        );
+        let konto_dienst: Arc<dyn KontoDienst> = speakeasy_chat::KontoService::neu(
+            Arc::clone(&db),
+            Arc::clone(&file_storage),
+            konto_konfig,
+        );
+        // Schluessel nur im Speicher: Download-Links verfallen beim Neustart
+        let upload_dienst: Arc<dyn UploadDienst> =
+            speakeasy_chat::UploadService::mit_zufallsschluessel(
+                Arc::clone(&file_service),
+                self.config.upload_konfig(),
+            );
+        let datei_dienst: Arc<dyn DateiDienst> =
+            speakeasy_chat::DownloadService::mit_zufallsschluessel(
+                file_service,
+                self.config.download_konfig(),
+            );
+        // Neue Dateien im Kanal melden, sobald der Signaling-Server steht
+        let upload_melder: Arc<OnceLock<http::UploadMelder>> = Arc::new(OnceLock::new());
+
+        // Datei-Server fuer Einmal-Links (Konto-Exporte) und Dateianhaenge
+        let datei_addr: SocketAddr = self.config.datei_http_bind_adresse().parse()?;
+        let (datei_shutdown_tx, datei_shutdown_rx) = tokio::sync::watch::channel(false);
+        let datei_handle = {
+            let state = http::DateiServerState {
+                konto: Arc::clone(&konto_dienst),
+                dateien: Arc::clone(&datei_dienst),
+                uploads: Arc::clone(&upload_dienst),
+                upload_melder: Arc::clone(&upload_melder),
+            };
+            tokio::spawn(async move {
+                if let Err(e) =
+                    http::datei_server_starten(datei_addr, state, datei_shutdown_rx).await
+                {
+                    tracing::error!(fehler = %e, "Datei-Server Fehler");
+                }
+            })
+        };
 
-        // --- 5. Voice-Server starten (UDP) ---
+        // --- 6. Voice-Server starten (UDP) ---
+        health.phase_setzen(StartPhase::ListenerBinden);
         let udp_addr: SocketAddr = self.config.udp_bind_adresse().parse()?;
-        let voice_router = ChannelRouter::neu();
-        let voice_state = VoiceState::neu();
-        let voice_config = VoiceServerConfig::neu(udp_addr);
+        // Gemeinsame Notfall-Stummschaltung: Signaling schaltet, UDP-Pfad verwirft
+        let notfall = NotfallStumm::neu();
+        let voice_router = ChannelRouter::mit_notfall(notfall.clone());
+        let zeitstempel_glaetten_ab = self
+            .config
+            .audio
+            .zeitstempel_glaetten_ab_ms
+            .map(|ms| Duration::from_millis(ms.into()));
+        voice_router.zeitstempel_glaetten_ab(zeitstempel_glaetten_ab);
+        // Gemeinsamer Sprecher-Tracker: UDP-Pfad schreibt, Signaling meldet
+        let sprecher = SprecherTracker::neu();
+        let voice_state = VoiceState::mit_sprecher(sprecher.clone());
+        let mut voice_config = VoiceServerConfig::neu(udp_addr);
+        voice_config.dscp = self.config.voice_dscp();
+        voice_config.empfangspuffer = self.config.netzwerk.voice_empfangspuffer_bytes;
+        voice_config.sendepuffer = self.config.netzwerk.voice_sendepuffer_bytes;
+        voice_config.ping = self.config.voice_ping();
+        voice_config.paket_ttl = self
+            .config
+            .audio
+            .paket_ttl_ms
+            .map(|ms| Duration::from_millis(ms.into()));
 
-        let voice_server =
+        // Gemeinsamer Aktivitaets-Tracker fuer Voice (Speaking) und Signaling (AFK)
+        let aktivitaet = AktivitaetsTracker::neu();
+
+        let mut voice_server =
             VoiceServer::binden(voice_config, voice_router.clone(), voice_state.clone())
                 .await
                 .map_err(|e| anyhow::anyhow!("Voice-Server konnte nicht binden: {e}"))?;
+        voice_server.aktivitaet_verfolgen(aktivitaet.clone());
+        voice_server.herzschlag_melden(
+            health
+                .komponenten()
+                .herzschlag_registrieren(SUBSYSTEM_VOICE, HERZSCHLAG_MAX_ALTER),
+        );
+        #[cfg(feature = "observability")]
+        let jitter_metriken = VoiceMetricsCollector::neu();
+        #[cfg(feature = "observability")]
+        voice_server.metriken_erfassen(jitter_metriken.clone());
+        health.subsystem_bereit(SUBSYSTEM_VOICE);
 
         let voice_server = Arc::new(voice_server);
         let (voice_shutdown_tx, voice_shutdown_rx) = tokio::sync::oneshot::channel::<()>();
@@ -145,10 +509,11 @@ impl Server {
 
         tracing::info!(
             adresse = %udp_addr,
+            dscp_aktiv = voice_server.qos_status().ist_aktiv(),
             "Voice-Server gestartet (UDP)"
         );
 
-        // --- 6. Signaling-Server starten (TCP) ---
+        // --- 7. Signaling-Server starten (TCP) ---
         // SignalingServer nutzt LocalSet wegen async_fn_in_trait ohne Send.
         // Deshalb starten wir ihn in einem eigenen Thread mit current_thread Runtime.
         // Krypto-Modus und DTLS-Fingerprint bestimmen
@@ -175,13 +540,36 @@ impl Server {
         };
 
         let signaling_config = SignalingConfig {
-            server_name: self.config.server.name.clone(),
-            welcome_message: self.config.server.willkommen.clone(),
-            max_clients: self.config.server.max_clients,
+            server_name: einstellungen.name,
+            welcome_message: einstellungen.willkommensnachricht,
+            max_clients: einstellungen.max_clients,
+            host_message: einstellungen.host_nachricht,
+            chat_bearbeitungsfrist_sek: einstellungen.chat_bearbeitungsfrist_sek,
+            motd,
             voice_udp_port: self.config.netzwerk.udp_port,
             voice_server_ip: self.config.netzwerk.bind_adresse.clone(),
             crypto_mode,
             dtls_fingerprint,
+            afk: self.config.afk_richtlinie(),
+            zeitlimits: self.config.zeitlimits(),
+            kanalbaum_teilweise_ab: self.config.server.kanalbaum_teilweise_ab as usize,
+            mitglieder_teilweise_ab: self.config.server.kanal_mitglieder_teilweise_ab as usize,
+            mitglieder_vorschau: self.config.server.kanal_mitglieder_vorschau as usize,
+            pcm_fallback_erlaubt: self.config.audio.pcm_fallback_erlaubt,
+            codec: CodecRichtlinie {
+                max_bitrate_kbps: u16::try_from(self.config.audio.max_bitrate_kbps)
+                    .unwrap_or(u16::MAX),
+                erlaubte_presets: self.config.audio.erlaubte_presets.clone(),
+            },
+            anfrage_limits: self.config.anfrage_limits(),
+            drosselung: self.config.drossel_limits(),
+            replay: self.config.replay_konfig(),
+            einladungen: self.config.einladungs_limits(),
+            mitschnitt: self.config.mitschnitt_konfig(),
+            registrierung: self.config.server.registrierung,
+            max_frame_bytes: self.config.netzwerk.max_frame_bytes,
+            hello_erforderlich: self.config.server.hello_erforderlich,
+            sitzungswarnung_sek: self.config.server.sitzungswarnung_sek,
             ..Default::default()
         };
 
@@ -195,9 +583,51 @@ impl Server {
             Arc::clone(&ban_service),
             Arc::clone(&db),
             Arc::clone(&chat_service),
+            aktivitaet,
+            sprecher,
+            notfall,
         );
 
-        let signaling_server = SignalingServer::neu(signaling_state, tcp_addr);
+        signaling_state.audit_sink_setzen(Arc::clone(&audit_puffer) as Arc<dyn AuditSink>);
+        signaling_state.konto_dienst_setzen(Arc::clone(&konto_dienst));
+        signaling_state.datei_dienst_setzen(datei_dienst);
+        signaling_state.upload_dienst_setzen(upload_dienst);
+        {
+            let state = Arc::clone(&signaling_state);
+            let _ = upload_melder.set(Arc::new(move |nachricht| {
+                speakeasy_signaling::handlers::chat_handler::datei_nachricht_melden(
+                    &state, nachricht,
+                );
+            }));
+        }
+        signaling_state.sound_quelle_setzen(Arc::clone(&db) as Arc<dyn SoundQuelle>);
+
+        // Zaehler aus Voice und Signaling regelmaessig nach `/metrics` uebernehmen
+        #[cfg(feature = "observability")]
+        let telemetrie = telemetrie::starten(
+            &voice_server,
+            &voice_state,
+            jitter_metriken,
+            signaling_state.anfragen.clone(),
+        );
+        signaling_state
+            .channel_router
+            .zeitstempel_glaetten_ab(zeitstempel_glaetten_ab);
+
+        // Broadcaster fuer Commander-Ereignisse (laeuft thread-uebergreifend)
+        let signaling_broadcaster = signaling_state.broadcaster.clone();
+        let afk_waechter = signaling_state.afk.clone();
+        let signaling_einstellungen = signaling_state.einstellungen.clone();
+        let signaling_fuer_commander = Arc::clone(&signaling_state);
+        let signaling_health = health.clone();
+        let signaling_server = SignalingServer::neu(signaling_state, tcp_addr)
+            .bei_bereitschaft(move |_| signaling_health.subsystem_bereit(SUBSYSTEM_SIGNALING))
+            .mit_herzschlag(
+                health
+                    .komponenten()
+                    .herzschlag_registrieren(SUBSYSTEM_SIGNALING, HERZSCHLAG_MAX_ALTER),
+            );
+        let signaling_health = health.clone();
 
         // Eigener Thread fuer LocalSet (nicht-Send Futures)
         let signaling_handle = std::thread::Builder::new()
@@ -210,6 +640,7 @@ impl Server {
                 rt.block_on(async move {
                     if let Err(e) = signaling_server.starten(signaling_shutdown_rx).await {
                         tracing::error!(fehler = %e, "Signaling-Server Fehler");
+                        signaling_health.start_fehlgeschlagen(format!("Signaling-Server: {e}"));
                     }
                 });
             })
@@ -220,20 +651,226 @@ impl Server {
             "Signaling-Server gestartet (TCP)"
         );
 
-        // --- 7. Commander starten (REST + gRPC) ---
+        // --- 8. Commander starten (REST + gRPC, optional TCP-ServerQuery) ---
         let commander_executor = CommandExecutor::neu(
             Arc::clone(&db), // user_repo
             Arc::clone(&db), // channel_repo
             Arc::clone(&db), // permission_repo
             Arc::clone(&db), // ban_repo
             Arc::clone(&db), // audit_repo
+            Arc::clone(&db), // file_repo
+            Arc::clone(&db), // template_repo
+            Arc::clone(&db), // settings_repo
+            Arc::clone(&db), // zeitplan_repo
             Arc::clone(&auth_service),
             Arc::clone(&permission_service),
             Arc::clone(&ban_service),
-            self.config.server.name.clone(),
+            standard_einstellungen,
             env!("CARGO_PKG_VERSION").to_string(),
+            KanalbaumGrenzen {
+                max_kanaele: self.config.server.max_kanaele,
+                max_tiefe: self.config.server.max_kanal_tiefe,
+            },
         );
 
+        // Sammel-Moves des Commanders laufen gegen die Signaling-Presence
+        let signaling_fuer_sprecher = Arc::clone(&signaling_fuer_commander);
+        let signaling_fuer_notfall = Arc::clone(&signaling_fuer_commander);
+        let signaling_fuer_konten = Arc::clone(&signaling_fuer_commander);
+        let signaling_fuer_bereinigung = Arc::clone(&signaling_fuer_commander);
+        #[cfg(feature = "observability")]
+        let signaling_fuer_alarme = Arc::clone(&signaling_fuer_commander);
+        commander_executor.audit_sink_setzen(Arc::clone(&audit_puffer) as Arc<dyn AuditSink>);
+        commander_executor.neustart_setzen(neustart);
+        commander_executor.features_setzen(
+            einkompilierte_features()
+                .into_iter()
+                .map(String::from)
+                .collect(),
+        );
+        commander_executor.signaling_setzen(Arc::new(SignalingAnbindung {
+            state: Arc::clone(&signaling_fuer_commander),
+        }));
+        commander_executor.client_verschieber_setzen(Arc::new(move |auftrag| {
+            let state = Arc::clone(&signaling_fuer_commander);
+            Box::pin(async move { sammel_verschiebung(&state, auftrag).await })
+        }));
+
+        // Notfall-Stummschaltung: Berechtigung, Ereignis und Audit im Signaling
+        commander_executor.notfall_stumm_setzen(Arc::new(move |auftrag| {
+            let state = Arc::clone(&signaling_fuer_notfall);
+            Box::pin(async move { notfall_stumm_schalten(&state, auftrag).await })
+        }));
+
+        // Aktive Sprecher fuer Dashboards (Momentaufnahme aus dem Sprecher-Tracker)
+        commander_executor.sprecher_abfrage_setzen(Arc::new(move |kanal_id| {
+            speakeasy_signaling::sprecher::aktive_sprecher(
+                &signaling_fuer_sprecher,
+                &ChannelId(kanal_id),
+            )
+            .into_iter()
+            .map(|id| id.inner())
+            .collect()
+        }));
+
+        // Voice-Diagnose pro Client (Netzwerkwerte und MOS aus dem Voice-State)
+        let voice_fuer_diagnose = voice_state.clone();
+        commander_executor.voice_diagnose_setzen(Arc::new(move |client_id| {
+            let client = voice_fuer_diagnose.client_state(&UserId(client_id))?;
+            Some(VoiceDiagnoseInfo {
+                user_id: client_id,
+                ssrc: client.ssrc,
+                kanal_id: client.kanal_id.map(|id| id.inner()),
+                rtt_ms: client.rtt_ms,
+                uplink_verlust: client.verlust_rate,
+                downlink_verlust: client.downlink_verlust_rate,
+                bitrate_kbps: client.bitrate.bitrate_kbps(),
+                mos: client.mos,
+            })
+        }));
+
+        // Datenexport und Kontoloeschung fuer Support-Faelle
+        let konto_fuer_export = Arc::clone(&konto_dienst);
+        commander_executor.konto_export_setzen(Arc::new(move |auftrag| {
+            let dienst = Arc::clone(&konto_fuer_export);
+            Box::pin(async move { konto_exportieren(dienst.as_ref(), auftrag).await })
+        }));
+        let konto_fuer_bereinigung = Arc::clone(&konto_dienst);
+        commander_executor.konto_loeschen_setzen(Arc::new(move |auftrag| {
+            let dienst = Arc::clone(&konto_dienst);
+            let state = Arc::clone(&signaling_fuer_konten);
+            Box::pin(async move { konto_loeschen(dienst.as_ref(), &state, auftrag).await })
+        }));
+
+        // Spam-Bereinigung (Ban-Option und eigener Befehl), Broadcast im Signaling
+        commander_executor.inhalte_bereinigen_setzen(Arc::new(move |auftrag| {
+            let dienst = Arc::clone(&konto_fuer_bereinigung);
+            let state = Arc::clone(&signaling_fuer_bereinigung);
+            Box::pin(async move { inhalte_bereinigen(dienst.as_ref(), &state, auftrag).await })
+        }));
+
+        // Soundboard: hochgeladene Dateien einmalig zu Opus-Frames kodieren
+        commander_executor.soundboard_kodierer_setzen(Arc::new(move |pfad, max_dauer| {
+            let speicher = Arc::clone(&file_storage);
+            Box::pin(async move { sound_kodieren(&speicher, pfad, max_dauer).await })
+        }));
+
+        // Sicherungen: Datenbank, Datei-Speicher und effektive Konfiguration.
+        // Nur mit SQLite – eine PostgreSQL-Datenbank sichert der Betreiber
+        // mit pg_dump.
+        if let Some(sqlite) = db.als_sqlite() {
+            let effektive_config = match toml::to_string(&self.config) {
+                Ok(config) => Some(config),
+                Err(e) => {
+                    tracing::warn!(
+                        fehler = %e,
+                        "Konfiguration nicht serialisierbar, Sicherungen ohne config.toml"
+                    );
+                    None
+                }
+            };
+            let sicherungs_quellen = Arc::new(SicherungsQuellen {
+                db: sqlite.clone(),
+                dateien: self.config.dateien.speicher_verzeichnis.clone().into(),
+                config: effektive_config,
+                server_version: env!("CARGO_PKG_VERSION").to_string(),
+            });
+            commander_executor.backup_setzen(Arc::new(move |auftrag| {
+                let quellen = Arc::clone(&sicherungs_quellen);
+                Box::pin(async move { sicherung::erstellen(&quellen, auftrag).await })
+            }));
+        } else {
+            tracing::info!(
+                backend = %db.backend(),
+                "Eingebaute Sicherungen nur mit SQLite verfuegbar, Datenbank mit pg_dump sichern"
+            );
+        }
+
+        // Eingebaute Alarmierung; der Commander kann jederzeit sofort pruefen
+        #[cfg(feature = "observability")]
+        let alarm_handle = {
+            let alarm_executor = Arc::downgrade(&commander_executor);
+            let alarmierung = Arc::new(Alarmierung::neu(
+                &self.config,
+                AlarmQuellen {
+                    db: Arc::clone(&db),
+                    health: health.clone(),
+                    signaling: signaling_fuer_alarme,
+                    commander: Arc::new(move |meldung| {
+                        if let Some(executor) = alarm_executor.upgrade() {
+                            executor.alarm_melden(meldung);
+                        }
+                    }),
+                    speicher: self.config.dateien.speicher_verzeichnis.clone().into(),
+                },
+            ));
+            let alarm_handle = if self.config.alarm.aktiviert {
+                tracing::info!(
+                    regeln = self.config.alarm.regeln.len(),
+                    intervall_sek = self.config.alarm_intervall().as_secs(),
+                    "Alarmierung gestartet"
+                );
+                Some(alarmierung.starten(self.config.alarm_intervall()))
+            } else {
+                tracing::info!("Alarmierung deaktiviert");
+                None
+            };
+            commander_executor.alarm_pruefung_setzen(Arc::new(move || {
+                let alarmierung = Arc::clone(&alarmierung);
+                Box::pin(async move { alarmierung.pruefen().await })
+            }));
+            alarm_handle
+        };
+
+        // Commander-Ereignisse an alle verbundenen Clients weiterreichen
+        let mut ereignisse = commander_executor.ereignisse_abonnieren();
+        tokio::spawn(async move {
+            use tokio::sync::broadcast::error::RecvError;
+            loop {
+                match ereignisse.recv().await {
+                    Ok(CommanderEreignis::AfkRichtlinieGeaendert {
+                        timeout_sek,
+                        kanal_id,
+                    }) => {
+                        afk_waechter.richtlinie_aendern(
+                            timeout_sek.map(|s| Duration::from_secs(s as u64)),
+                            kanal_id.map(ChannelId),
+                        );
+                    }
+                    Ok(CommanderEreignis::ServerEinstellungenGeaendert(neu)) => {
+                        signaling_einstellungen.uebernehmen(neu);
+                    }
+                    Ok(CommanderEreignis::MotdGeaendert(neu)) => {
+                        signaling_einstellungen.motd_uebernehmen(neu);
+                        signaling_broadcaster.an_alle_senden(ControlMessage::new(
+                            0,
+                            ControlPayload::MotdChanged(MotdChangedEvent {
+                                motd: signaling_einstellungen.motd(),
+                            }),
+                        ));
+                    }
+                    Ok(CommanderEreignis::Sicherung(fortschritt)) => {
+                        tracing::debug!(
+                            ziel = %fortschritt.ziel,
+                            phase = ?fortschritt.phase,
+                            aktuell = fortschritt.aktuell,
+                            gesamt = fortschritt.gesamt,
+                            "Sicherungs-Fortschritt"
+                        );
+                    }
+                    Ok(ereignis) => {
+                        if let Some(nachricht) = ereignis_zu_nachricht(ereignis) {
+                            signaling_broadcaster.an_alle_senden(nachricht);
+                        }
+                    }
+                    Err(RecvError::Lagged(n)) => {
+                        tracing::warn!(verpasst = n, "Commander-Ereignisse verworfen");
+                    }
+                    Err(RecvError::Closed) => break,
+                }
+            }
+        });
+
         // Type-erased executor closure fuer CommanderState
         let executor_arc = Arc::clone(&commander_executor);
         let executor_fn: ExecutorFn = Arc::new(move |cmd, session| {
@@ -245,6 +882,7 @@ impl Server {
         let commander_auth = Arc::new(speakeasy_commander::auth::CommanderAuth::neu(Arc::clone(
             &auth_service,
         )));
+        let zertifikats_auth = Arc::clone(&commander_auth);
         let token_validator: TokenValidatorFn = Arc::new(move |token: &str| {
             let auth = Arc::clone(&commander_auth);
             let token = token.to_string();
@@ -254,22 +892,74 @@ impl Server {
             })
         });
 
-        let commander_state = CommanderState::neu(executor_fn, token_validator);
+        let mut commander_state = CommanderState::neu(executor_fn, token_validator)
+            .mit_zeitlimits(self.config.zeitlimits());
+
+        // Client-Zertifikate anstelle eines Tokens (nur bei mTLS im Modus "ersetzend")
+        let commander_tls = self.config.commander.tls.as_ref();
+        if let Some(zuordnungen) = commander_tls.and_then(|tls| tls.ersetzende_zuordnungen()) {
+            let zuordnungen = zuordnungen.to_vec();
+            tracing::info!(
+                zuordnungen = zuordnungen.len(),
+                "Commander: Client-Zertifikate ersetzen den Bearer-Token"
+            );
+            let zertifikats_validator: ZertifikatsValidatorFn =
+                Arc::new(move |identitaet: &ClientIdentitaet| {
+                    tokio::task::block_in_place(|| {
+                        tokio::runtime::Handle::current().block_on(
+                            zertifikats_auth.zertifikat_validieren(identitaet, &zuordnungen),
+                        )
+                    })
+                });
+            commander_state = commander_state.mit_zertifikats_validator(zertifikats_validator);
+        }
+
+        // Zeitplaner fuer geplante Aktionen
+        let (zeitplaner_shutdown_tx, zeitplaner_shutdown_rx) = tokio::sync::watch::channel(false);
+        let zeitplaner_handle = if self.config.zeitplaner.aktiviert {
+            let zeitplaner = Zeitplaner::neu(
+                Arc::clone(&db),
+                Arc::clone(&db),
+                commander_state.clone(),
+                Arc::new(SystemUhr),
+                Duration::from_secs(u64::from(self.config.zeitplaner.verfallszeit_min) * 60),
+            );
+            tracing::info!(
+                verfallszeit_min = self.config.zeitplaner.verfallszeit_min,
+                "Zeitplaner gestartet"
+            );
+            Some(tokio::spawn(async move {
+                zeitplaner.laufen(zeitplaner_shutdown_rx).await;
+            }))
+        } else {
+            tracing::info!("Zeitplaner deaktiviert");
+            None
+        };
 
         // REST-Server
         let rest_addr: SocketAddr = self.config.commander_rest_bind_adresse().parse()?;
         let rest_konfig = speakeasy_commander::rest::server::RestServerKonfig {
             bind_addr: rest_addr,
             cors_origins: self.config.commander.cors_origins.clone(),
+            tls: commander_tls.map(|tls| tls.tls_konfig()),
         };
         let rate_limiter = RateLimiter::neu(RateLimitKonfig::default());
+        let (commander_shutdown_tx, commander_shutdown_rx) = tokio::sync::watch::channel(false);
 
         let rest_state = commander_state.clone();
         let rest_limiter = Arc::clone(&rate_limiter);
+        let rest_health = health.clone();
+        let rest_shutdown_rx = commander_shutdown_rx.clone();
         let rest_handle = tokio::spawn(async move {
-            let server = speakeasy_commander::rest::RestServer::neu(rest_konfig);
-            if let Err(e) = server.starten(rest_state, rest_limiter).await {
+            let bereit_health = rest_health.clone();
+            let server = speakeasy_commander::rest::RestServer::neu(rest_konfig)
+                .bei_bereitschaft(move |_| bereit_health.subsystem_bereit(SUBSYSTEM_COMMANDER));
+            if let Err(e) = server
+                .starten(rest_state, rest_limiter, rest_shutdown_rx)
+                .await
+            {
                 tracing::error!(fehler = %e, "REST-Commander-Server Fehler");
+                rest_health.start_fehlgeschlagen(format!("REST-Commander-Server: {e}"));
             }
         });
 
@@ -279,46 +969,79 @@ impl Server {
         );
 
         // gRPC-Server
-        let grpc_addr: SocketAddr = self.config.grpc_bind_adresse().parse()?;
-        let grpc_konfig = speakeasy_commander::grpc::GrpcServerKonfig {
-            bind_addr: grpc_addr,
-        };
+        #[cfg(feature = "grpc")]
+        let grpc_handle = {
+            let grpc_addr: SocketAddr = self.config.grpc_bind_adresse().parse()?;
+            let grpc_konfig = speakeasy_commander::grpc::GrpcServerKonfig {
+                bind_addr: grpc_addr,
+                tls: commander_tls.map(|tls| tls.tls_konfig()),
+            };
 
-        let grpc_state = commander_state.clone();
-        let grpc_handle = tokio::spawn(async move {
-            let server = speakeasy_commander::grpc::GrpcServer::neu(grpc_konfig);
-            if let Err(e) = server.starten(grpc_state).await {
-                tracing::error!(fehler = %e, "gRPC-Commander-Server Fehler");
-            }
-        });
+            let grpc_state = commander_state.clone();
+            let grpc_shutdown_rx = commander_shutdown_rx.clone();
+            let grpc_handle = tokio::spawn(async move {
+                let server = speakeasy_commander::grpc::GrpcServer::neu(grpc_konfig);
+                if let Err(e) = server.starten(grpc_state, grpc_shutdown_rx).await {
+                    tracing::error!(fehler = %e, "gRPC-Commander-Server Fehler");
+                }
+            });
 
-        tracing::info!(
-            adresse = %grpc_addr,
-            "Commander gRPC-Server gestartet"
-        );
+            tracing::info!(
+                adresse = %grpc_addr,
+                "Commander gRPC-Server gestartet"
+            );
+            grpc_handle
+        };
 
-        // --- 8. Observability starten ---
-        let obs_handle = if self.config.observability.aktiviert {
-            let obs_addr: SocketAddr = self.config.observability_bind_adresse().parse()?;
+        // TCP-ServerQuery (line-based, optional ueber TLS)
+        #[cfg(feature = "tcp-commander")]
+        let tcp_handle = if self.config.commander.tcp_aktiviert {
+            let tcp_addr: SocketAddr = self.config.commander_tcp_bind_adresse().parse()?;
+            let tcp_konfig = speakeasy_commander::tcp::TcpServerKonfig {
+                bind_addr: tcp_addr,
+                max_verbindungen: self.config.commander.tcp_max_verbindungen,
+                tls: self
+                    .config
+                    .commander
+                    .tcp_tls
+                    .as_ref()
+                    .map(|tls| tls.tls_konfig()),
+                ..Default::default()
+            };
+            if tcp_konfig.tls.is_none() {
+                tracing::warn!(
+                    adresse = %tcp_addr,
+                    "Commander TCP-ServerQuery ohne TLS, Tokens werden im Klartext uebertragen"
+                );
+            }
+            let tcp_state = commander_state.clone();
+            let tcp_limiter = Arc::clone(&rate_limiter);
+            let tcp_health = health.clone();
             let handle = tokio::spawn(async move {
-                if let Err(e) =
-                    speakeasy_observability::observability_server_starten(obs_addr).await
+                let server = speakeasy_commander::tcp::TcpServer::neu(tcp_konfig);
+                if let Err(e) = server
+                    .starten(tcp_state, tcp_limiter, commander_shutdown_rx)
+                    .await
                 {
-                    tracing::error!(fehler = %e, "Observability-Server Fehler");
+                    tracing::error!(fehler = %e, "TCP-Commander-Server Fehler");
+                    tcp_health.start_fehlgeschlagen(format!("TCP-Commander-Server: {e}"));
                 }
             });
             tracing::info!(
-                adresse = %self.config.observability_bind_adresse(),
-                "Observability-Server gestartet (Metriken + Health)"
+                adresse = %tcp_addr,
+                "Commander TCP-Server gestartet"
             );
             Some(handle)
         } else {
-            tracing::info!("Observability deaktiviert");
+            tracing::info!("Commander TCP-ServerQuery deaktiviert");
             None
         };
+        #[cfg(not(feature = "tcp-commander"))]
+        let tcp_handle = None;
 
         // --- 9. Plugin-Manager ---
-        let _plugin_manager = if self.config.plugins.aktiviert {
+        #[cfg(feature = "plugins")]
+        let plugin_manager = if self.config.plugins.aktiviert {
             let manager = PluginManager::neu(ManagerKonfiguration::default());
             tracing::info!(
                 verzeichnis = ?self.config.plugins.verzeichnis,
@@ -330,46 +1053,477 @@ impl Server {
             None
         };
 
-        // --- 10. Warten auf Shutdown-Signal ---
-        tracing::info!(
-            "Server laeuft. Alle Subsysteme gestartet. Warte auf Shutdown-Signal (Ctrl-C)..."
-        );
-        tokio::signal::ctrl_c().await?;
-        tracing::info!("Shutdown-Signal empfangen, fahre Server herunter...");
+        // Startsequenz durch; bereit sobald Signaling und Commander gebunden haben
+        health.phase_setzen(StartPhase::Abgeschlossen);
 
-        // Graceful Shutdown aller Services
+        Ok(Laufend {
+            voice_shutdown_tx,
+            voice_handle,
+            #[cfg(feature = "observability")]
+            telemetrie,
+            #[cfg(feature = "observability")]
+            alarm_handle,
+            signaling_shutdown_tx,
+            signaling_handle,
+            datei_shutdown_tx,
+            datei_handle,
+            zeitplaner_shutdown_tx,
+            zeitplaner_handle,
+            commander_shutdown_tx,
+            commander_shutdown_zeitlimit: self.config.commander_shutdown_zeitlimit(),
+            rest_handle,
+            #[cfg(feature = "grpc")]
+            grpc_handle,
+            tcp_handle,
+            zugriffs_log,
+            audit_puffer,
+            shutdown_marker,
+            #[cfg(feature = "plugins")]
+            _plugin_manager: plugin_manager,
+        })
+    }
+}
+
+/// Gestartete Subsysteme, die beim Shutdown beendet werden
+struct Laufend {
+    voice_shutdown_tx: tokio::sync::oneshot::Sender<()>,
+    voice_handle: tokio::task::JoinHandle<()>,
+    #[cfg(feature = "observability")]
+    telemetrie: Vec<tokio::task::JoinHandle<()>>,
+    #[cfg(feature = "observability")]
+    alarm_handle: Option<tokio::task::JoinHandle<()>>,
+    signaling_shutdown_tx: tokio::sync::watch::Sender<bool>,
+    signaling_handle: std::thread::JoinHandle<()>,
+    datei_shutdown_tx: tokio::sync::watch::Sender<bool>,
+    datei_handle: tokio::task::JoinHandle<()>,
+    zeitplaner_shutdown_tx: tokio::sync::watch::Sender<bool>,
+    zeitplaner_handle: Option<tokio::task::JoinHandle<()>>,
+    commander_shutdown_tx: tokio::sync::watch::Sender<bool>,
+    commander_shutdown_zeitlimit: Duration,
+    rest_handle: tokio::task::JoinHandle<()>,
+    #[cfg(feature = "grpc")]
+    grpc_handle: tokio::task::JoinHandle<()>,
+    tcp_handle: Option<tokio::task::JoinHandle<()>>,
+    zugriffs_log: Arc<speakeasy_chat::ZugriffsLogger>,
+    audit_puffer: Arc<AuditPuffer>,
+    shutdown_marker: PathBuf,
+    #[cfg(feature = "plugins")]
+    _plugin_manager: Option<PluginManager>,
+}
+
+impl Laufend {
+    /// Graceful Shutdown aller Services
+    async fn herunterfahren(self) {
         // Voice-Server stoppen
-        let _ = voice_shutdown_tx.send(());
+        let _ = self.voice_shutdown_tx.send(());
         tracing::debug!("Voice-Server Shutdown-Signal gesendet");
 
+        // Telemetrie und Alarmierung stoppen (waehrend des Shutdowns keine Fehlalarme)
+        #[cfg(feature = "observability")]
+        {
+            for handle in &self.telemetrie {
+                handle.abort();
+            }
+            if let Some(handle) = &self.alarm_handle {
+                handle.abort();
+            }
+        }
+
         // Signaling-Server stoppen
-        let _ = signaling_shutdown_tx.send(true);
+        let _ = self.signaling_shutdown_tx.send(true);
         tracing::debug!("Signaling-Server Shutdown-Signal gesendet");
 
-        // Commander-Tasks abbrechen (keine graceful shutdown API)
-        rest_handle.abort();
-        grpc_handle.abort();
-        tracing::debug!("Commander-Server gestoppt");
+        // Zeitplaner stoppen (laufende Aktion wird noch abgeschlossen)
+        let _ = self.zeitplaner_shutdown_tx.send(true);
+        if let Some(handle) = self.zeitplaner_handle {
+            let _ = handle.await;
+            tracing::debug!("Zeitplaner gestoppt");
+        }
 
-        // Observability stoppen
-        if let Some(handle) = obs_handle {
-            handle.abort();
-            tracing::debug!("Observability-Server gestoppt");
+        // Datei-Server stoppen (laufende Downloads werden abgeschlossen)
+        let _ = self.datei_shutdown_tx.send(true);
+        let _ = self.datei_handle.await;
+        tracing::debug!("Datei-Server gestoppt");
+
+        // Commander stoppen: laufende Anfragen duerfen bis zum Zeitlimit fertig werden
+        let _ = self.commander_shutdown_tx.send(true);
+        let mut rest_handle = self.rest_handle;
+        #[cfg(feature = "grpc")]
+        let mut grpc_handle = self.grpc_handle;
+        let mut tcp_handle = self.tcp_handle;
+        let rechtzeitig = tokio::time::timeout(self.commander_shutdown_zeitlimit, async {
+            let _ = (&mut rest_handle).await;
+            #[cfg(feature = "grpc")]
+            let _ = (&mut grpc_handle).await;
+            if let Some(handle) = &mut tcp_handle {
+                let _ = handle.await;
+            }
+        })
+        .await;
+        if rechtzeitig.is_err() {
+            tracing::warn!(
+                zeitlimit_sek = self.commander_shutdown_zeitlimit.as_secs(),
+                "Commander-Server nicht rechtzeitig beendet, laufende Anfragen werden abgebrochen"
+            );
+            rest_handle.abort();
+            #[cfg(feature = "grpc")]
+            grpc_handle.abort();
+            if let Some(handle) = &tcp_handle {
+                handle.abort();
+            }
         }
+        tracing::debug!("Commander-Server gestoppt");
 
         // Voice-Task abwarten
-        let _ = voice_handle.await;
+        let _ = self.voice_handle.await;
         tracing::debug!("Voice-Server beendet");
 
         // Signaling-Thread abwarten
-        if let Err(e) = signaling_handle.join() {
+        if let Err(e) = self.signaling_handle.join() {
             tracing::warn!("Signaling-Thread Fehler beim Beenden: {:?}", e);
         }
         tracing::debug!("Signaling-Server beendet");
 
-        tracing::info!("Server erfolgreich heruntergefahren");
+        // Ausstehende Zugriffsprotokoll-Eintraege schreiben
+        self.zugriffs_log.leeren().await;
 
-        Ok(())
+        // Ausstehende Audit-Ereignisse schreiben (alle Erzeuger sind beendet)
+        self.audit_puffer.leeren().await;
+        let verworfen = self.audit_puffer.verworfen();
+        if verworfen > 0 {
+            tracing::warn!(verworfen, "Audit-Ereignisse seit dem Start verworfen");
+        }
+
+        // Erst ganz zum Schluss: der naechste Start sieht einen sauberen Lauf
+        if let Err(e) = speakeasy_db::neustart::sauber_beendet(&self.shutdown_marker) {
+            tracing::warn!(
+                pfad = %self.shutdown_marker.display(),
+                fehler = %e,
+                "Shutdown-Marker konnte nicht geschrieben werden"
+            );
+        }
+    }
+}
+
+/// Meldet die Belegung des Dateispeichers als Komponente von `/health`
+///
+/// Existiert das Verzeichnis noch nicht (vor dem ersten Upload), zaehlt der
+/// Datentraeger des naechsten vorhandenen Elternverzeichnisses.
+#[cfg(feature = "observability")]
+fn speicher_pruefung_registrieren(health: &HealthState, verzeichnis: &str) {
+    let verzeichnis = PathBuf::from(verzeichnis);
+    health
+        .komponenten()
+        .pruefung_registrieren(KOMPONENTE_SPEICHER, move || {
+            let verzeichnis = verzeichnis.clone();
+            async move {
+                // statvfs kann auf haengenden Netzlaufwerken blockieren
+                let belegung = tokio::task::spawn_blocking(move || {
+                    let vorhanden = verzeichnis
+                        .ancestors()
+                        .find(|p| p.exists())
+                        .unwrap_or(std::path::Path::new("."));
+                    speakeasy_observability::alarm::speicher_belegung(vorhanden)
+                })
+                .await;
+                match belegung {
+                    Ok(Some(prozent)) if prozent >= SPEICHER_GRENZE_PROZENT => {
+                        Befund::eingeschraenkt(format!("{prozent:.1} % belegt"))
+                    }
+                    Ok(Some(prozent)) => {
+                        Befund::gesund().mit_detail(format!("{prozent:.1} % belegt"))
+                    }
+                    _ => Befund::ausgefallen("Belegung nicht messbar"),
+                }
+            }
+        });
+}
+
+/// Uebersetzt ein Commander-Ereignis in eine Server->Client Nachricht
+///
+/// Gibt `None` fuer rein serverseitige Ereignisse zurueck.
+fn ereignis_zu_nachricht(ereignis: CommanderEreignis) -> Option<ControlMessage> {
+    match ereignis {
+        CommanderEreignis::KanalbaumGeaendert {
+            wurzel_id,
+            parent_id,
+            kanaele,
+        } => Some(ControlMessage::new(
+            0,
+            ControlPayload::ChannelTreeChanged(ChannelTreeChanged {
+                root_id: ChannelId(wurzel_id),
+                parent_id: parent_id.map(ChannelId),
+                created: kanaele.into_iter().map(ChannelId).collect(),
+            }),
+        )),
+        CommanderEreignis::AfkRichtlinieGeaendert { .. }
+        | CommanderEreignis::ServerEinstellungenGeaendert(_)
+        | CommanderEreignis::MotdGeaendert(_)
+        | CommanderEreignis::Sicherung(_)
+        | CommanderEreignis::Alarm(_) => None,
+    }
+}
+
+/// Kick, Move und Poke des Commanders gegen die Signaling-Presence
+struct SignalingAnbindung {
+    state: Arc<SignalingState<Datenbank, Datenbank, Datenbank>>,
+}
+
+impl SignalingNotifier for SignalingAnbindung {
+    fn kicken(&self, user_id: Uuid, grund: Option<String>) -> BoxFuture<'_, CommanderResult<()>> {
+        Box::pin(async move {
+            moderation::kicken(&self.state, UserId(user_id), grund.as_deref())
+                .map_err(signaling_fehler)
+        })
+    }
+
+    fn verschieben(&self, user_id: Uuid, kanal_id: Uuid) -> BoxFuture<'_, CommanderResult<()>> {
+        Box::pin(async move {
+            moderation::verschieben(&self.state, UserId(user_id), ChannelId(kanal_id))
+                .await
+                .map_err(signaling_fehler)
+        })
+    }
+
+    fn anstupsen(&self, user_id: Uuid, nachricht: String) -> BoxFuture<'_, CommanderResult<()>> {
+        Box::pin(async move {
+            moderation::anstupsen(&self.state, UserId(user_id), nachricht)
+                .map_err(signaling_fehler)
+        })
+    }
+
+    fn kanal_geaendert(&self, kanal_id: Uuid) -> BoxFuture<'_, CommanderResult<()>> {
+        Box::pin(async move {
+            kanal_geaendert_melden(&self.state, ChannelId(kanal_id))
+                .await
+                .map_err(signaling_fehler)
+        })
+    }
+
+    fn ip_adresse(&self, user_id: Uuid) -> CommanderResult<String> {
+        self.state
+            .presence
+            .client_presence(&UserId(user_id))
+            .and_then(|p| p.ip)
+            .ok_or_else(|| CommanderError::NichtGefunden(format!("Client {user_id} nicht verbunden")))
+    }
+}
+
+/// Fuehrt einen Sammel-Move des Commanders im Signaling-Dienst aus
+async fn sammel_verschiebung(
+    state: &Arc<SignalingState<Datenbank, Datenbank, Datenbank>>,
+    auftrag: SammelVerschiebung,
+) -> CommanderResult<SammelVerschiebungErgebnis> {
+    let anfrage = ClientsMoveAllRequest {
+        from_channel_id: ChannelId(auftrag.von_kanal_id),
+        to_channel_id: ChannelId(auftrag.nach_kanal_id),
+        only_user_ids: auftrag
+            .nur_user_ids
+            .map(|ids| ids.into_iter().map(UserId).collect()),
+        allow_partial: auftrag.teilweise,
+        reason: None,
+    };
+    let antwort = clients_alle_verschieben(state, UserId(auftrag.aktor_id), anfrage)
+        .await
+        .map_err(signaling_fehler)?;
+
+    Ok(SammelVerschiebungErgebnis {
+        verschoben: antwort.moved.into_iter().map(|id| id.inner()).collect(),
+        uebersprungen: antwort
+            .skipped
+            .into_iter()
+            .map(|s| UebersprungenerClient {
+                user_id: s.user_id.inner(),
+                grund: match s.reason {
+                    MoveSkipReason::NotInChannel => "not_in_channel",
+                    MoveSkipReason::InsufficientPower => "insufficient_power",
+                    MoveSkipReason::ChannelFull => "channel_full",
+                }
+                .into(),
+            })
+            .collect(),
+    })
+}
+
+/// Schaltet die Notfall-Stummschaltung eines Kanals im Signaling-Dienst
+async fn notfall_stumm_schalten(
+    state: &Arc<SignalingState<Datenbank, Datenbank, Datenbank>>,
+    auftrag: NotfallStummAuftrag,
+) -> CommanderResult<NotfallStummErgebnis> {
+    let event = kanal_notfall_stumm(
+        state,
+        UserId(auftrag.aktor_id),
+        ChannelId(auftrag.kanal_id),
+        auftrag.aktivieren,
+    )
+    .await
+    .map_err(signaling_fehler)?;
+
+    Ok(NotfallStummErgebnis {
+        kanal_id: event.channel_id.inner(),
+        aktiv: event.active,
+        ausgenommen: event.exempt.into_iter().map(|id| id.inner()).collect(),
+    })
+}
+
+/// Erstellt einen Datenexport im Auftrag eines Administrators
+///
+/// Ohne Sperrfrist; das Archiv wird sofort geschrieben.
+async fn konto_exportieren(
+    dienst: &dyn KontoDienst,
+    auftrag: KontoAuftrag,
+) -> CommanderResult<KontoExportErgebnis> {
+    let export = dienst
+        .export_anfordern(auftrag.benutzer_id, false)
+        .await
+        .map_err(chat_fehler)?;
+    let fertig = dienst
+        .export_erstellen(&export)
+        .await
+        .map_err(chat_fehler)?;
+
+    Ok(KontoExportErgebnis {
+        export_id: fertig.export_id,
+        download_url: fertig.download_url,
+        laeuft_ab: fertig.expires_at,
+        groesse_bytes: fertig.size_bytes,
+    })
+}
+
+/// Loescht ein Konto im Auftrag eines Administrators
+///
+/// Beendet Sessions und API-Tokens und trennt den Benutzer, falls er gerade
+/// verbunden ist.
+async fn konto_loeschen(
+    dienst: &dyn KontoDienst,
+    state: &Arc<SignalingState<Datenbank, Datenbank, Datenbank>>,
+    auftrag: KontoAuftrag,
+) -> CommanderResult<KontoLoeschErgebnis> {
+    let loeschung = dienst
+        .konto_loeschen(auftrag.benutzer_id, Some(auftrag.aktor_id))
+        .await
+        .map_err(chat_fehler)?;
+    let (sessions, api_tokens) = state
+        .auth_service
+        .zugaenge_beenden(auftrag.benutzer_id)
+        .await;
+
+    // Verbundenen Client benachrichtigen und trennen (wie beim Kick)
+    let user_id = UserId(auftrag.benutzer_id);
+    if state.presence.ist_online(&user_id) {
+        client_trennen(
+            state,
+            user_id,
+            ControlMessage::error(0, ErrorCode::InvalidRequest, "Dein Konto wurde geloescht"),
+        );
+    }
+
+    Ok(KontoLoeschErgebnis {
+        benutzer_id: auftrag.benutzer_id,
+        nachrichten: loeschung.nachrichten,
+        dateien: loeschung.dateien.len() as u64,
+        berechtigungen: loeschung.berechtigungen,
+        sessions: sessions as u64,
+        api_tokens: api_tokens as u64,
+    })
+}
+
+/// Entfernt Nachrichten und Dateien eines Benutzers oder setzt einen Lauf fort
+///
+/// Ein fortgesetzter Lauf muss zum angegebenen Benutzer gehoeren.
+async fn inhalte_bereinigen(
+    dienst: &dyn KontoDienst,
+    state: &Arc<SignalingState<Datenbank, Datenbank, Datenbank>>,
+    auftrag: BereinigungsAuftrag,
+) -> CommanderResult<BereinigungsErgebnis> {
+    let aktor_id = Some(UserId(auftrag.aktor_id));
+    let ergebnis = match auftrag.job_id {
+        Some(job_id) => {
+            let job = dienst
+                .bereinigung_laden(job_id)
+                .await
+                .map_err(chat_fehler)?;
+            if job.user_id != auftrag.benutzer_id {
+                return Err(CommanderError::UngueltigeEingabe(format!(
+                    "Bereinigung {job_id} gehoert zu einem anderen Benutzer"
+                )));
+            }
+            bereinigung::fortsetzen(state, job_id, aktor_id).await
+        }
+        None => {
+            bereinigung::bereinigen(
+                state,
+                Bereinigungsauftrag {
+                    user_id: UserId(auftrag.benutzer_id),
+                    zeitraum: auftrag.zeitraum,
+                    nur_kanal: auftrag.nur_kanal.map(ChannelId),
+                    aktor_id,
+                },
+            )
+            .await
+        }
+    }
+    .map_err(signaling_fehler)?;
+
+    Ok(BereinigungsErgebnis {
+        job_id: ergebnis.job_id,
+        benutzer_id: ergebnis.user_id.inner(),
+        nachrichten: ergebnis.nachrichten,
+        dateien: ergebnis.dateien,
+        kanaele: ergebnis
+            .pro_kanal
+            .into_iter()
+            .map(|(kanal_id, zaehler)| KanalBereinigungInfo {
+                kanal_id,
+                nachrichten: zaehler.nachrichten,
+                dateien: zaehler.dateien,
+            })
+            .collect(),
+    })
+}
+
+/// Kodiert eine hochgeladene WAV-Datei fuer das Soundboard
+///
+/// Das Kodieren ist CPU-lastig und laeuft daher im Blocking-Pool.
+async fn sound_kodieren(
+    speicher: &speakeasy_chat::DiskStorage,
+    pfad: String,
+    max_dauer: Duration,
+) -> CommanderResult<KodierterSound> {
+    let daten = speicher.retrieve(&pfad).await.map_err(chat_fehler)?;
+    let clip =
+        tokio::task::spawn_blocking(move || speakeasy_audio::wav_clip_kodieren(&daten, max_dauer))
+            .await
+            .map_err(|e| CommanderError::Intern(anyhow::anyhow!(e)))?
+            .map_err(|e| match e {
+                // Kein WAV, leer oder zu lang
+                AudioError::Konfiguration(grund) => CommanderError::UngueltigeEingabe(grund),
+                andere => CommanderError::Intern(anyhow::anyhow!(andere)),
+            })?;
+    Ok(KodierterSound {
+        frames: clip.frames,
+        frame_ms: speakeasy_audio::clip::CLIP_FRAME_MS,
+    })
+}
+
+/// Uebersetzt Fehler des Kontodienstes fuer den Commander
+fn chat_fehler(e: ChatError) -> CommanderError {
+    match e {
+        ChatError::DatenbankFehler(db) => CommanderError::Datenbank(db),
+        ChatError::UngueltigeEingabe(m) => CommanderError::UngueltigeEingabe(m),
+        andere => CommanderError::Intern(anyhow::anyhow!(andere)),
+    }
+}
+
+/// Uebersetzt Fehler des Signaling-Dienstes fuer den Commander
+fn signaling_fehler(e: SignalingError) -> CommanderError {
+    match e {
+        SignalingError::ZugriffVerweigert(m) => CommanderError::NichtAutorisiert(m),
+        SignalingError::NichtGefunden(m) => CommanderError::NichtGefunden(m),
+        SignalingError::KanalVoll | SignalingError::Protokoll(_) => {
+            CommanderError::UngueltigeEingabe(e.to_string())
+        }
+        andere => CommanderError::Intern(anyhow::anyhow!(andere)),
     }
 }
 
@@ -378,8 +1532,8 @@ impl Server {
 /// Anschliessend wird geprueft ob ein Default-Channel existiert.
 /// Wenn nicht, wird ein permanenter "Default Channel" angelegt.
 async fn ersten_start_initialisieren(
-    db: &SqliteDb,
-    auth_service: &AuthService<SqliteDb>,
+    db: &Datenbank,
+    auth_service: &AuthService<Datenbank>,
 ) -> Result<()> {
     let alle_benutzer = UserRepository::list(db, false)
         .await
@@ -425,7 +1579,7 @@ async fn ersten_start_initialisieren(
 
 /// Stellt sicher dass mindestens ein Default-Channel existiert.
 /// Legt "Default Channel" an wenn keiner vorhanden ist.
-async fn default_channel_initialisieren(db: &SqliteDb) -> Result<()> {
+async fn default_channel_initialisieren(db: &Datenbank) -> Result<()> {
     let vorhandener_default = ChannelRepository::get_default(db)
         .await
         .map_err(|e| anyhow::anyhow!("Default-Channel-Pruefung fehlgeschlagen: {e}"))?;
@@ -1,8 +1,31 @@
 //! Speakeasy Server – Einstiegspunkt
 //!
 //! Laedt die Konfiguration, initialisiert das Logging und startet den Server.
+//!
+//! Mit `--import-ts3 <pfad>` wird statt des Servers ein TS3-Snapshot in die
+//! konfigurierte Datenbank importiert und der Bericht als JSON ausgegeben:
+//!
+//! ```text
+//! speakeasy-server --import-ts3 snapshot.txt [--dry-run]
+//!     [--benutzer platzhalter|einladung] [--ersteller <username>]
+//! ```
+//!
+//! Mit `--restore <sicherung>` wird eine Sicherung (siehe
+//! `speakeasy_commander::sicherung`) offline in ein leeres Datenverzeichnis
+//! wiederhergestellt. Die dort abgelegte `config.toml` zeigt auf die
+//! wiederhergestellte Datenbank und den Datei-Speicher:
+//!
+//! ```text
+//! speakeasy-server --restore /var/backups/speakeasy-2026-10-16 --ziel /srv/speakeasy
+//! SPEAKEASY_CONFIG=/srv/speakeasy/config.toml speakeasy-server
+//! ```
 
-use anyhow::Result;
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context, Result};
+use speakeasy_commander::sicherung;
+use speakeasy_commander::ts3_import::{self, BenutzerStrategie, ImportOptionen};
+use speakeasy_db::{models::KanalbaumGrenzen, repository::UserRepository, Datenbank};
 use speakeasy_server::{config::ServerConfig, Server};
 
 #[tokio::main]
@@ -16,6 +39,14 @@ async fn main() -> Result<()> {
     // Logging initialisieren
     logging_initialisieren(&config.logging.level, &config.logging.format);
 
+    let argumente: Vec<String> = std::env::args().skip(1).collect();
+    if argumente.iter().any(|a| a == "--import-ts3") {
+        return ts3_importieren(&config, &argumente).await;
+    }
+    if argumente.iter().any(|a| a == "--restore") {
+        return wiederherstellen(&config, &argumente).await;
+    }
+
     tracing::info!(
         version = env!("CARGO_PKG_VERSION"),
         config = %config_pfad,
@@ -29,6 +60,111 @@ async fn main() -> Result<()> {
     Ok(())
 }
 
+/// Fuehrt den TS3-Import aus (`--import-ts3`) und gibt den Bericht aus
+async fn ts3_importieren(config: &ServerConfig, argumente: &[String]) -> Result<()> {
+    let mut pfad = None;
+    let mut probelauf = false;
+    let mut strategie = BenutzerStrategie::Platzhalter;
+    let mut ersteller = None;
+
+    let mut iter = argumente.iter();
+    while let Some(argument) = iter.next() {
+        match argument.as_str() {
+            "--import-ts3" => pfad = iter.next().cloned(),
+            "--dry-run" => probelauf = true,
+            "--benutzer" => {
+                let wert = iter.next().context("--benutzer erwartet einen Wert")?;
+                strategie = wert.parse().map_err(anyhow::Error::msg)?;
+            }
+            "--ersteller" => ersteller = iter.next().cloned(),
+            anderes => bail!("Unbekanntes Argument fuer den TS3-Import: {anderes}"),
+        }
+    }
+    let pfad = pfad.context("--import-ts3 erwartet den Pfad zum Snapshot")?;
+    let text = std::fs::read_to_string(&pfad)
+        .with_context(|| format!("Snapshot '{pfad}' konnte nicht gelesen werden"))?;
+
+    let db = Datenbank::oeffnen(&config.datenbank_config()?)
+        .await
+        .map_err(|e| anyhow::anyhow!("Datenbankverbindung fehlgeschlagen: {e}"))?;
+
+    let ersteller_id = match ersteller {
+        Some(name) => Some(
+            db.get_by_name(&name)
+                .await?
+                .with_context(|| format!("Ersteller '{name}' nicht gefunden"))?
+                .id,
+        ),
+        None => None,
+    };
+
+    let bericht = ts3_import::importieren(
+        &db,
+        &text,
+        &ImportOptionen {
+            strategie,
+            probelauf,
+            ersteller_id,
+            grenzen: KanalbaumGrenzen {
+                max_kanaele: config.server.max_kanaele,
+                max_tiefe: config.server.max_kanal_tiefe,
+            },
+        },
+    )
+    .await?;
+
+    println!("{}", serde_json::to_string_pretty(&bericht)?);
+    Ok(())
+}
+
+/// Stellt eine Sicherung offline wieder her (`--restore`) und gibt den Bericht aus
+///
+/// Das Ziel muss leer sein oder darf noch nicht existieren. Enthaelt die
+/// Sicherung keine Konfiguration, wird die aktuelle als Grundlage genommen.
+async fn wiederherstellen(config: &ServerConfig, argumente: &[String]) -> Result<()> {
+    let mut archiv = None;
+    let mut ziel = None;
+
+    let mut iter = argumente.iter();
+    while let Some(argument) = iter.next() {
+        match argument.as_str() {
+            "--restore" => archiv = iter.next().cloned(),
+            "--ziel" => ziel = iter.next().cloned(),
+            anderes => bail!("Unbekanntes Argument fuer die Wiederherstellung: {anderes}"),
+        }
+    }
+    let archiv = archiv.context("--restore erwartet den Pfad zur Sicherung")?;
+    let ziel = PathBuf::from(ziel.context("--ziel erwartet das (leere) Datenverzeichnis")?);
+
+    let bericht = sicherung::wiederherstellen(Path::new(&archiv), &ziel).await?;
+
+    // Datenbank und Datei-Speicher der Konfiguration auf das Ziel umstellen
+    let ziel = ziel.canonicalize()?;
+    let config_pfad = ziel.join(sicherung::CONFIG_DATEI);
+    let mut wiederhergestellt = if config_pfad.exists() {
+        ServerConfig::laden(&config_pfad.to_string_lossy())?
+    } else {
+        config.clone()
+    };
+    wiederhergestellt.datenbank.url = format!(
+        "sqlite://{}",
+        ziel.join(sicherung::DATENBANK_DATEI).display()
+    );
+    wiederhergestellt.dateien.speicher_verzeichnis = ziel
+        .join(sicherung::DATEIEN_VERZEICHNIS)
+        .display()
+        .to_string();
+    std::fs::write(&config_pfad, toml::to_string(&wiederhergestellt)?).with_context(|| {
+        format!(
+            "'{}' konnte nicht geschrieben werden",
+            config_pfad.display()
+        )
+    })?;
+
+    println!("{}", serde_json::to_string_pretty(&bericht)?);
+    Ok(())
+}
+
 /// Initialisiert tracing-subscriber mit dem konfigurierten Level und Format
 fn logging_initialisieren(level: &str, format: &str) {
     use tracing_subscriber::{fmt, EnvFilter};
@@ -0,0 +1,152 @@
+//! Telemetrie – Zaehler aus Voice und Signaling fuer `/metrics`
+//!
+//! Die Subsysteme fuehren eigene Zaehler; die Tasks hier uebernehmen sie im
+//! Takt von [`TELEMETRIE_INTERVALL`] in die Prometheus-Registry. Nur mit dem
+//! Feature `observability` enthalten.
+
+use std::sync::Arc;
+
+use speakeasy_observability::metrics::globale_metriken;
+use speakeasy_signaling::anfragelimit::AnfrageBegrenzer;
+use speakeasy_voice::telemetry::{SocketAbtaster, VoiceMetricsCollector, TELEMETRIE_INTERVALL};
+use speakeasy_voice::udp::VoiceServer;
+use speakeasy_voice::VoiceState;
+use tokio::task::JoinHandle;
+
+/// Startet alle Telemetrie-Tasks; beim Shutdown werden sie abgebrochen
+pub(crate) fn starten(
+    voice_server: &Arc<VoiceServer>,
+    voice_state: &VoiceState,
+    jitter_metriken: VoiceMetricsCollector,
+    anfragen: AnfrageBegrenzer,
+) -> Vec<JoinHandle<()>> {
+    // Kernel-Drops des Voice-Sockets in die Prometheus-Metriken uebernehmen
+    let socket_abtaster =
+        SocketAbtaster::neu(Arc::clone(voice_server)).starten(TELEMETRIE_INTERVALL, |snapshot| {
+            let metriken = globale_metriken();
+            metriken
+                .voice_socket_rx_drops_total
+                .inc_by(snapshot.neue_drops);
+            metriken
+                .voice_socket_rx_queue_bytes
+                .set(snapshot.zaehler.empfangs_warteschlange as f64);
+            metriken
+                .voice_socket_rx_buffer_bytes
+                .set(snapshot.puffer.empfang as f64);
+        });
+
+    // Jitter-Buffer-Statistik pro Absender; /metrics liest dieselbe Registry
+    let jitter = jitter_metriken.starten(voice_state.clone(), TELEMETRIE_INTERVALL, |snapshot| {
+        let metriken = globale_metriken();
+        for ssrc in &snapshot.entfernt {
+            metriken.ssrc_entfernen(&ssrc.to_string());
+        }
+        for metrik in &snapshot.ssrcs {
+            let ssrc = metrik.ssrc.to_string();
+            let labels = [ssrc.as_str()];
+            let statistik = &metrik.statistik;
+            // Zaehler fuehren den Stand des Jitter Buffers nach
+            for (zaehler, stand) in [
+                (&metriken.voice_packets_received_total, statistik.empfangen),
+                (&metriken.voice_packets_lost_total, statistik.verloren),
+                (&metriken.voice_packets_duplicate_total, statistik.duplikate),
+            ] {
+                let zaehler = zaehler.with_label_values(&labels);
+                zaehler.inc_by(stand.saturating_sub(zaehler.get()));
+            }
+            metriken
+                .voice_jitter_ticks
+                .with_label_values(&labels)
+                .set(statistik.jitter_ticks.into());
+            metriken
+                .voice_buffer_fill
+                .with_label_values(&labels)
+                .set(statistik.fuellstand as i64);
+        }
+    });
+
+    // Vom Voice-Server verworfene Pakete (Verspaetung, Nur-Zuhoerer,
+    // Notfall-Stummschaltung, unbekannte Absender, abgelehnte Hellos) und beim
+    // Empfang ignorierte ICMP-Rueckmeldungen
+    let verworfen = {
+        let voice_server = Arc::clone(voice_server);
+        tokio::spawn(async move {
+            let (mut veraltet, mut nur_hoeren, mut notfall, mut unerreichbar) = (0, 0, 0, 0);
+            let (mut unbekannt, mut abgelehnt) = (0, 0);
+            let mut ticker = tokio::time::interval(TELEMETRIE_INTERVALL);
+            loop {
+                ticker.tick().await;
+                let metriken = globale_metriken();
+                let stand = voice_server.veraltet_verworfen();
+                metriken.voice_stale_drops_total.inc_by(stand - veraltet);
+                veraltet = stand;
+                let stand = voice_server.nur_hoeren_verworfen();
+                metriken
+                    .voice_listen_only_drops_total
+                    .inc_by(stand - nur_hoeren);
+                nur_hoeren = stand;
+                let stand = voice_server.notfall_verworfen();
+                metriken
+                    .voice_emergency_mute_drops_total
+                    .inc_by(stand - notfall);
+                notfall = stand;
+                let stand = voice_server.empfang_unerreichbar();
+                metriken
+                    .voice_icmp_unreachable_total
+                    .inc_by(stand - unerreichbar);
+                unerreichbar = stand;
+                let stand = voice_server.unbekannt_verworfen();
+                metriken
+                    .voice_unknown_source_drops_total
+                    .inc_by(stand - unbekannt);
+                unbekannt = stand;
+                let stand = voice_server.hello_abgelehnt();
+                metriken
+                    .voice_hello_rejected_total
+                    .inc_by(stand - abgelehnt);
+                abgelehnt = stand;
+            }
+        })
+    };
+
+    // Mittlere Sprachqualitaet je Kanal aus den Noten der Sitzungen
+    let mos = {
+        let voice_state = voice_state.clone();
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(TELEMETRIE_INTERVALL);
+            loop {
+                ticker.tick().await;
+                let werte: Vec<(String, f64)> = voice_state
+                    .mos_pro_kanal()
+                    .into_iter()
+                    .map(|(kanal_id, mos)| (kanal_id.inner().to_string(), mos))
+                    .collect();
+                globale_metriken()
+                    .kanal_mos_setzen(werte.iter().map(|(kanal, mos)| (kanal.as_str(), *mos)));
+            }
+        })
+    };
+
+    // Laufende und abgelehnte Signaling-Anfragen in die Metriken uebernehmen
+    let anfragen = tokio::spawn(async move {
+        let mut abgelehnt = 0;
+        let mut ticker = tokio::time::interval(TELEMETRIE_INTERVALL);
+        loop {
+            ticker.tick().await;
+            let metriken = globale_metriken();
+            let stand = anfragen.statistik();
+            metriken
+                .signaling_requests_in_flight
+                .set(stand.laufend as f64);
+            metriken
+                .signaling_db_requests_in_flight
+                .set(stand.db_laufend as f64);
+            metriken
+                .signaling_requests_rejected_total
+                .inc_by(stand.abgelehnt - abgelehnt);
+            abgelehnt = stand.abgelehnt;
+        }
+    });
+
+    vec![socket_abtaster, jitter, verworfen, mos, anfragen]
+}